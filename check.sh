@@ -45,6 +45,14 @@ else
     exit 1
 fi
 
+# Check the portable build (core, geometry and io without `native`)
+if rustup target list --installed 2>/dev/null | grep -q wasm32-unknown-unknown; then
+    print_status "Running portable wasm32 check..."
+    cargo check --no-default-features --target wasm32-unknown-unknown
+else
+    print_warning "wasm32 target not installed. Install with: rustup target add wasm32-unknown-unknown"
+fi
+
 # Run clippy if available
 if command -v cargo-clippy &> /dev/null; then
     print_status "Running clippy..."
@@ -0,0 +1,158 @@
+/*
+ * CADDY - Enterprise CAD System
+ * C ABI for embedding CADDY documents and geometry.
+ *
+ * Link against libcaddy_ffi (cdylib or staticlib built from ffi/).
+ *
+ * Conventions:
+ *   - Fallible functions return CaddyStatus; on failure, caddy_last_error()
+ *     returns a message for the calling thread.
+ *   - Strings are UTF-8 and NUL-terminated. Input strings are borrowed for
+ *     the duration of the call; output strings are copied into caller buffers.
+ *   - Entities are addressed by index in [0, caddy_document_entity_count()).
+ *     Indices shift on removal; use CaddyEntityInfo.id for stable identity.
+ *   - A CaddyDocument must not be used from two threads at once.
+ */
+
+#ifndef CADDY_H
+#define CADDY_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum CaddyStatus {
+    CADDY_OK = 0,
+    CADDY_NULL_POINTER = 1,
+    CADDY_INVALID_ARGUMENT = 2,
+    CADDY_NOT_FOUND = 3,
+    CADDY_IO = 4,
+    CADDY_FORMAT = 5,
+    CADDY_COMMAND = 6,
+    CADDY_UNSUPPORTED = 7,
+    CADDY_BUFFER_TOO_SMALL = 8,
+    CADDY_PANIC = 99
+} CaddyStatus;
+
+typedef enum CaddyEntityKind {
+    CADDY_ENTITY_POINT = 0,
+    CADDY_ENTITY_LINE = 1,
+    CADDY_ENTITY_CIRCLE = 2,
+    CADDY_ENTITY_ARC = 3,
+    CADDY_ENTITY_ELLIPSE = 4,
+    CADDY_ENTITY_POLYLINE = 5,
+    CADDY_ENTITY_SPLINE = 6,
+    CADDY_ENTITY_TEXT = 7,
+    CADDY_ENTITY_MTEXT = 8,
+    CADDY_ENTITY_DIMENSION = 9,
+    CADDY_ENTITY_INSERT = 10,
+    CADDY_ENTITY_HATCH = 11,
+    CADDY_ENTITY_POINT_CLOUD = 12
+} CaddyEntityKind;
+
+typedef struct CaddyPoint3 {
+    double x;
+    double y;
+    double z;
+} CaddyPoint3;
+
+typedef struct CaddyBounds {
+    CaddyPoint3 min;
+    CaddyPoint3 max;
+} CaddyBounds;
+
+typedef struct CaddyEntityInfo {
+    uint8_t id[16];          /* UUID bytes, big-endian */
+    CaddyEntityKind kind;
+    bool visible;
+    CaddyBounds bounds;
+} CaddyEntityInfo;
+
+/* Opaque document handle */
+typedef struct CaddyDocument CaddyDocument;
+
+/* ---- Library ----------------------------------------------------------- */
+
+/* Static version string, e.g. "0.3.0" */
+const char *caddy_version(void);
+
+/* Message for the last failure on this thread, or NULL. Valid until the
+ * next failing call on the same thread. */
+const char *caddy_last_error(void);
+
+/* ---- Document lifecycle ------------------------------------------------ */
+
+CaddyDocument *caddy_document_new(void);
+void caddy_document_free(CaddyDocument *doc);
+
+/* Load .cdy, .cdyj, or .dxf; *out_doc receives a new handle */
+CaddyStatus caddy_document_load(const char *path, CaddyDocument **out_doc);
+
+/* Save as .cdy, .cdyj, or .dxf depending on the extension of `path` */
+CaddyStatus caddy_document_save(const CaddyDocument *doc, const char *path);
+
+/* Replace the contents of `doc` with DXF text held in memory */
+CaddyStatus caddy_document_read_dxf(CaddyDocument *doc, const char *text);
+
+/* ---- Entity iteration -------------------------------------------------- */
+
+size_t caddy_document_entity_count(const CaddyDocument *doc);
+
+CaddyStatus caddy_document_entity_info(const CaddyDocument *doc, size_t index,
+                                       CaddyEntityInfo *out);
+
+/* Copy the layer name into `buffer`. `*out_len` (may be NULL) receives the
+ * required size including the terminator; CADDY_BUFFER_TOO_SMALL if short. */
+CaddyStatus caddy_document_entity_layer(const CaddyDocument *doc, size_t index,
+                                        char *buffer, size_t buffer_len,
+                                        size_t *out_len);
+
+CaddyStatus caddy_document_find_entity(const CaddyDocument *doc,
+                                       const uint8_t id[16], size_t *out_index);
+
+/* ---- Geometry queries -------------------------------------------------- */
+
+/* CADDY_NOT_FOUND when the document is empty */
+CaddyStatus caddy_document_bounds(const CaddyDocument *doc, CaddyBounds *out);
+
+/* Lines, circles, arcs, polylines; CADDY_UNSUPPORTED otherwise */
+CaddyStatus caddy_entity_length(const CaddyDocument *doc, size_t index,
+                                double *out);
+
+/* Circles, ellipses, closed polylines; CADDY_UNSUPPORTED otherwise */
+CaddyStatus caddy_entity_area(const CaddyDocument *doc, size_t index,
+                              double *out);
+
+/* Closest visible entity to (x, y) within `tolerance` drawing units */
+CaddyStatus caddy_document_pick(const CaddyDocument *doc, double x, double y,
+                                double tolerance, size_t *out_index);
+
+/* ---- Editing and commands ---------------------------------------------- */
+
+/* `layer` may be NULL for layer "0"; `out_index` may be NULL */
+CaddyStatus caddy_document_add_line(CaddyDocument *doc, CaddyPoint3 start,
+                                    CaddyPoint3 end, const char *layer,
+                                    size_t *out_index);
+
+CaddyStatus caddy_document_add_circle(CaddyDocument *doc, CaddyPoint3 center,
+                                      double radius, const char *layer,
+                                      size_t *out_index);
+
+CaddyStatus caddy_document_remove_entity(CaddyDocument *doc, size_t index);
+
+/* Run a command line such as "UNDO"; created/erased entities are reflected
+ * in the entity list on return */
+CaddyStatus caddy_document_execute(CaddyDocument *doc, const char *command_line);
+
+/* Feed input to the active multi-step command */
+CaddyStatus caddy_document_command_input(CaddyDocument *doc, const char *input);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CADDY_H */
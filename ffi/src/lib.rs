@@ -0,0 +1,1066 @@
+//! # CADDY C ABI
+//!
+//! Stable C bindings for embedding CADDY documents in C, C++, C#, or Python
+//! applications. The matching header lives in `include/caddy.h`.
+//!
+//! ## Conventions
+//!
+//! - Documents are opaque `CaddyDocument*` handles created by
+//!   `caddy_document_new`/`caddy_document_load` and released with
+//!   `caddy_document_free`.
+//! - Fallible functions return a [`CaddyStatus`]; on failure a message is
+//!   available from `caddy_last_error` on the same thread.
+//! - Strings are UTF-8 and NUL-terminated. Strings passed in are borrowed;
+//!   strings copied out go into caller-provided buffers.
+//! - Entities are addressed by their index in the document, in `0..count`.
+//!   Indices shift when entities are removed; use the 16-byte id from
+//!   [`CaddyEntityInfo`] to track an entity across edits.
+//! - Panics never cross the boundary; they are reported as
+//!   [`CaddyStatus::Panic`].
+
+use caddy::commands::{self, CommandContext, CommandProcessor};
+use caddy::geometry::{Arc2D, LineSegment2D, Point2D, Polygon2D};
+use caddy::io::document::{
+    Arc, BoundingBox, Circle, Document, Entity, GeometryType, Line, Vec3,
+};
+use caddy::io::dxf::{DxfReader, DxfVersion, DxfWriter};
+use caddy::io::native::{FormatDetector, JsonFormat, NativeFormat};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use uuid::Uuid;
+
+/// Result codes returned by every fallible function
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaddyStatus {
+    /// Success
+    Ok = 0,
+    /// A required pointer argument was null
+    NullPointer = 1,
+    /// An argument was out of range or not valid UTF-8
+    InvalidArgument = 2,
+    /// The requested entity or value does not exist
+    NotFound = 3,
+    /// File system error
+    Io = 4,
+    /// The file could not be parsed or written in the requested format
+    Format = 5,
+    /// Command execution failed
+    Command = 6,
+    /// The query is not defined for this entity type
+    Unsupported = 7,
+    /// The caller's buffer is too small; the required size was reported
+    BufferTooSmall = 8,
+    /// An internal panic was caught at the boundary
+    Panic = 99,
+}
+
+/// Entity type tags
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaddyEntityKind {
+    /// Point
+    Point = 0,
+    /// Line segment
+    Line = 1,
+    /// Full circle
+    Circle = 2,
+    /// Circular arc
+    Arc = 3,
+    /// Ellipse
+    Ellipse = 4,
+    /// Polyline (optionally closed, may contain bulges)
+    Polyline = 5,
+    /// B-spline / NURBS curve
+    Spline = 6,
+    /// Single-line text
+    Text = 7,
+    /// Multi-line text
+    MText = 8,
+    /// Dimension
+    Dimension = 9,
+    /// Block reference
+    Insert = 10,
+    /// Hatch
+    Hatch = 11,
+    /// Point cloud
+    PointCloud = 12,
+}
+
+impl From<&GeometryType> for CaddyEntityKind {
+    fn from(geometry: &GeometryType) -> Self {
+        match geometry {
+            GeometryType::Point(_) => CaddyEntityKind::Point,
+            GeometryType::Line(_) => CaddyEntityKind::Line,
+            GeometryType::Circle(_) => CaddyEntityKind::Circle,
+            GeometryType::Arc(_) => CaddyEntityKind::Arc,
+            GeometryType::Ellipse(_) => CaddyEntityKind::Ellipse,
+            GeometryType::Polyline(_) => CaddyEntityKind::Polyline,
+            GeometryType::Spline(_) => CaddyEntityKind::Spline,
+            GeometryType::Text(_) => CaddyEntityKind::Text,
+            GeometryType::MText(_) => CaddyEntityKind::MText,
+            GeometryType::Dimension(_) => CaddyEntityKind::Dimension,
+            GeometryType::Insert(_) => CaddyEntityKind::Insert,
+            GeometryType::Hatch(_) => CaddyEntityKind::Hatch,
+            GeometryType::PointCloud(_) => CaddyEntityKind::PointCloud,
+        }
+    }
+}
+
+/// 3D point / vector
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CaddyPoint3 {
+    /// X coordinate
+    pub x: f64,
+    /// Y coordinate
+    pub y: f64,
+    /// Z coordinate
+    pub z: f64,
+}
+
+impl From<Vec3> for CaddyPoint3 {
+    fn from(v: Vec3) -> Self {
+        Self { x: v.x, y: v.y, z: v.z }
+    }
+}
+
+impl From<CaddyPoint3> for Vec3 {
+    fn from(p: CaddyPoint3) -> Self {
+        Vec3::new(p.x, p.y, p.z)
+    }
+}
+
+/// Axis-aligned bounding box
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CaddyBounds {
+    /// Minimum corner
+    pub min: CaddyPoint3,
+    /// Maximum corner
+    pub max: CaddyPoint3,
+}
+
+impl From<BoundingBox> for CaddyBounds {
+    fn from(b: BoundingBox) -> Self {
+        Self {
+            min: b.min.into(),
+            max: b.max.into(),
+        }
+    }
+}
+
+/// Summary of one entity, filled by `caddy_document_entity_info`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CaddyEntityInfo {
+    /// Stable entity id (UUID bytes, big-endian)
+    pub id: [u8; 16],
+    /// Entity type
+    pub kind: CaddyEntityKind,
+    /// Whether the entity itself is visible (layer state not considered)
+    pub visible: bool,
+    /// Entity extents
+    pub bounds: CaddyBounds,
+}
+
+/// Opaque document handle
+pub struct CaddyDocument {
+    document: Document,
+    processor: CommandProcessor,
+    context: CommandContext,
+    /// Command-system entities already mirrored into `document`
+    command_entities: HashMap<commands::EntityId, Uuid>,
+}
+
+impl CaddyDocument {
+    fn new(document: Document) -> Self {
+        Self {
+            document,
+            processor: commands::create_standard_processor(),
+            context: CommandContext::new(commands::Document::new()),
+            command_entities: HashMap::new(),
+        }
+    }
+
+    /// Mirror entities created or removed by commands into the document
+    fn sync_command_entities(&mut self) {
+        let live = &self.context.document.entities;
+
+        let removed: Vec<commands::EntityId> = self
+            .command_entities
+            .keys()
+            .filter(|id| !live.contains_key(id))
+            .copied()
+            .collect();
+        for id in removed {
+            if let Some(uuid) = self.command_entities.remove(&id) {
+                self.document.remove_entity(uuid);
+            }
+        }
+
+        let layer = self.context.document.current_layer.clone();
+        for (id, data) in live {
+            if self.command_entities.contains_key(id) {
+                continue;
+            }
+            if let Some(geometry) = command_geometry(data.as_ref()) {
+                let uuid = self
+                    .document
+                    .add_entity(Entity::new(geometry, layer.clone()));
+                self.command_entities.insert(*id, uuid);
+            }
+        }
+    }
+}
+
+/// Translate the tuple payloads stored by the draw commands
+fn command_geometry(data: &(dyn std::any::Any + Send + Sync)) -> Option<GeometryType> {
+    let vec = |p: &commands::Point| Vec3::new(p.x, p.y, p.z);
+
+    if let Some((start, end)) = data.downcast_ref::<(commands::Point, commands::Point)>() {
+        Some(GeometryType::Line(Line {
+            start: vec(start),
+            end: vec(end),
+        }))
+    } else if let Some((center, radius)) = data.downcast_ref::<(commands::Point, f64)>() {
+        Some(GeometryType::Circle(Circle {
+            center: vec(center),
+            radius: *radius,
+            normal: Vec3::unit_z(),
+        }))
+    } else if let Some((center, radius, start, end)) =
+        data.downcast_ref::<(commands::Point, f64, f64, f64)>()
+    {
+        // Command input angles are in degrees
+        Some(GeometryType::Arc(Arc {
+            center: vec(center),
+            radius: *radius,
+            start_angle: start.to_radians(),
+            end_angle: end.to_radians(),
+            normal: Vec3::unit_z(),
+        }))
+    } else {
+        None
+    }
+}
+
+// ============================================================================
+// Error handling
+// ============================================================================
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl Into<String>) {
+    let message = message.into().replace('\0', " ");
+    LAST_ERROR.with(|e| *e.borrow_mut() = CString::new(message).ok());
+}
+
+fn fail(status: CaddyStatus, message: impl Into<String>) -> CaddyStatus {
+    set_last_error(message);
+    status
+}
+
+/// Run `f`, converting panics into [`CaddyStatus::Panic`]
+fn guard<F>(f: F) -> CaddyStatus
+where
+    F: FnOnce() -> CaddyStatus,
+{
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(status) => status,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            fail(CaddyStatus::Panic, format!("internal error: {}", message))
+        }
+    }
+}
+
+/// Borrow a C string argument as `&str`
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a NUL-terminated string that outlives `'a`.
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, CaddyStatus> {
+    if ptr.is_null() {
+        return Err(fail(CaddyStatus::NullPointer, format!("{} is null", name)));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| fail(CaddyStatus::InvalidArgument, format!("{} is not UTF-8", name)))
+}
+
+/// Borrow a document handle
+///
+/// # Safety
+///
+/// `doc` must be null or a live handle from this library.
+unsafe fn doc_ref<'a>(doc: *const CaddyDocument) -> Result<&'a CaddyDocument, CaddyStatus> {
+    doc.as_ref()
+        .ok_or_else(|| fail(CaddyStatus::NullPointer, "document is null"))
+}
+
+/// Mutably borrow a document handle
+///
+/// # Safety
+///
+/// `doc` must be null or a live handle from this library, not aliased.
+unsafe fn doc_mut<'a>(doc: *mut CaddyDocument) -> Result<&'a mut CaddyDocument, CaddyStatus> {
+    doc.as_mut()
+        .ok_or_else(|| fail(CaddyStatus::NullPointer, "document is null"))
+}
+
+fn entity_at(doc: &CaddyDocument, index: usize) -> Result<&Entity, CaddyStatus> {
+    doc.document.entities.get(index).ok_or_else(|| {
+        fail(
+            CaddyStatus::NotFound,
+            format!(
+                "entity index {} out of range (count {})",
+                index,
+                doc.document.entities.len()
+            ),
+        )
+    })
+}
+
+macro_rules! try_status {
+    ($expr:expr) => {
+        match $expr {
+            Ok(value) => value,
+            Err(status) => return status,
+        }
+    };
+}
+
+// ============================================================================
+// Library
+// ============================================================================
+
+/// Library version as a static NUL-terminated string
+#[no_mangle]
+pub extern "C" fn caddy_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+/// Message for the last failed call on this thread, or null
+///
+/// The pointer stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn caddy_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| {
+        e.borrow()
+            .as_ref()
+            .map(|s| s.as_ptr())
+            .unwrap_or(std::ptr::null())
+    })
+}
+
+// ============================================================================
+// Document lifecycle
+// ============================================================================
+
+/// Create an empty document. Never returns null.
+#[no_mangle]
+pub extern "C" fn caddy_document_new() -> *mut CaddyDocument {
+    Box::into_raw(Box::new(CaddyDocument::new(Document::new())))
+}
+
+/// Release a document. Passing null is a no-op.
+///
+/// # Safety
+///
+/// `doc` must be null or a handle from this library that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_document_free(doc: *mut CaddyDocument) {
+    if !doc.is_null() {
+        drop(Box::from_raw(doc));
+    }
+}
+
+/// Load a document, choosing the format from the file extension or content
+///
+/// Supports `.cdy`, `.cdyj`, and `.dxf`. On success `*out_doc` receives a new
+/// handle owned by the caller.
+///
+/// # Safety
+///
+/// `path` must be a NUL-terminated string; `out_doc` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_document_load(
+    path: *const c_char,
+    out_doc: *mut *mut CaddyDocument,
+) -> CaddyStatus {
+    guard(|| {
+        let path = try_status!(str_arg(path, "path"));
+        if out_doc.is_null() {
+            return fail(CaddyStatus::NullPointer, "out_doc is null");
+        }
+        if !Path::new(path).exists() {
+            return fail(CaddyStatus::Io, format!("{}: file not found", path));
+        }
+
+        match FormatDetector::load(path) {
+            Ok(document) => {
+                *out_doc = Box::into_raw(Box::new(CaddyDocument::new(document)));
+                CaddyStatus::Ok
+            }
+            Err(e) => fail(CaddyStatus::Format, format!("{}: {}", path, e)),
+        }
+    })
+}
+
+/// Save a document; the format follows the extension (`.cdy`, `.cdyj`, `.dxf`)
+///
+/// # Safety
+///
+/// `doc` must be a live handle; `path` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_document_save(
+    doc: *const CaddyDocument,
+    path: *const c_char,
+) -> CaddyStatus {
+    guard(|| {
+        let doc = try_status!(doc_ref(doc));
+        let path = try_status!(str_arg(path, "path"));
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .unwrap_or_default();
+
+        let result = match extension.as_str() {
+            "dxf" => DxfWriter::new(DxfVersion::R2018)
+                .write_file(&doc.document, path)
+                .map_err(|e| e.to_string()),
+            "cdyj" | "json" => JsonFormat::new()
+                .save(&doc.document, path)
+                .map_err(|e| e.to_string()),
+            "cdy" => NativeFormat::new()
+                .save(&doc.document, path)
+                .map_err(|e| e.to_string()),
+            other => {
+                return fail(
+                    CaddyStatus::InvalidArgument,
+                    format!("unsupported save format '{}'", other),
+                )
+            }
+        };
+
+        match result {
+            Ok(()) => CaddyStatus::Ok,
+            Err(e) => fail(CaddyStatus::Io, format!("{}: {}", path, e)),
+        }
+    })
+}
+
+/// Replace the document contents with DXF text held in memory
+///
+/// # Safety
+///
+/// `doc` must be a live handle; `text` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_document_read_dxf(
+    doc: *mut CaddyDocument,
+    text: *const c_char,
+) -> CaddyStatus {
+    guard(|| {
+        let doc = try_status!(doc_mut(doc));
+        let text = try_status!(str_arg(text, "text"));
+        match DxfReader::new().read(text.as_bytes()) {
+            Ok(document) => {
+                *doc = CaddyDocument::new(document);
+                CaddyStatus::Ok
+            }
+            Err(e) => fail(CaddyStatus::Format, e.to_string()),
+        }
+    })
+}
+
+// ============================================================================
+// Entity iteration
+// ============================================================================
+
+/// Number of entities in the document (0 for a null handle)
+///
+/// # Safety
+///
+/// `doc` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_document_entity_count(doc: *const CaddyDocument) -> usize {
+    doc.as_ref().map(|d| d.document.entities.len()).unwrap_or(0)
+}
+
+/// Describe the entity at `index`
+///
+/// # Safety
+///
+/// `doc` must be a live handle; `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_document_entity_info(
+    doc: *const CaddyDocument,
+    index: usize,
+    out: *mut CaddyEntityInfo,
+) -> CaddyStatus {
+    guard(|| {
+        let doc = try_status!(doc_ref(doc));
+        if out.is_null() {
+            return fail(CaddyStatus::NullPointer, "out is null");
+        }
+        let entity = try_status!(entity_at(doc, index));
+        *out = CaddyEntityInfo {
+            id: *entity.id.as_bytes(),
+            kind: (&entity.geometry).into(),
+            visible: entity.visible,
+            bounds: entity.bounding_box().into(),
+        };
+        CaddyStatus::Ok
+    })
+}
+
+/// Copy the layer name of the entity at `index` into `buffer`
+///
+/// `*out_len` (optional) receives the required size including the NUL. If
+/// `buffer_len` is too small nothing is written and
+/// [`CaddyStatus::BufferTooSmall`] is returned.
+///
+/// # Safety
+///
+/// `doc` must be a live handle; `buffer` must hold `buffer_len` bytes;
+/// `out_len` must be null or valid.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_document_entity_layer(
+    doc: *const CaddyDocument,
+    index: usize,
+    buffer: *mut c_char,
+    buffer_len: usize,
+    out_len: *mut usize,
+) -> CaddyStatus {
+    guard(|| {
+        let doc = try_status!(doc_ref(doc));
+        let entity = try_status!(entity_at(doc, index));
+        copy_out(&entity.layer, buffer, buffer_len, out_len)
+    })
+}
+
+/// Find the index of an entity by its 16-byte id
+///
+/// # Safety
+///
+/// `doc` must be a live handle; `id` must point to 16 bytes; `out_index`
+/// must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_document_find_entity(
+    doc: *const CaddyDocument,
+    id: *const u8,
+    out_index: *mut usize,
+) -> CaddyStatus {
+    guard(|| {
+        let doc = try_status!(doc_ref(doc));
+        if id.is_null() || out_index.is_null() {
+            return fail(CaddyStatus::NullPointer, "id or out_index is null");
+        }
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(std::slice::from_raw_parts(id, 16));
+        let uuid = Uuid::from_bytes(bytes);
+
+        match doc.document.entities.iter().position(|e| e.id == uuid) {
+            Some(index) => {
+                *out_index = index;
+                CaddyStatus::Ok
+            }
+            None => fail(CaddyStatus::NotFound, format!("no entity with id {}", uuid)),
+        }
+    })
+}
+
+unsafe fn copy_out(
+    value: &str,
+    buffer: *mut c_char,
+    buffer_len: usize,
+    out_len: *mut usize,
+) -> CaddyStatus {
+    let needed = value.len() + 1;
+    if !out_len.is_null() {
+        *out_len = needed;
+    }
+    if buffer.is_null() || buffer_len < needed {
+        return fail(
+            CaddyStatus::BufferTooSmall,
+            format!("buffer of {} bytes needed", needed),
+        );
+    }
+    std::ptr::copy_nonoverlapping(value.as_ptr() as *const c_char, buffer, value.len());
+    *buffer.add(value.len()) = 0;
+    CaddyStatus::Ok
+}
+
+// ============================================================================
+// Geometry queries
+// ============================================================================
+
+/// Extents of all entities; [`CaddyStatus::NotFound`] for an empty document
+///
+/// # Safety
+///
+/// `doc` must be a live handle; `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_document_bounds(
+    doc: *const CaddyDocument,
+    out: *mut CaddyBounds,
+) -> CaddyStatus {
+    guard(|| {
+        let doc = try_status!(doc_ref(doc));
+        if out.is_null() {
+            return fail(CaddyStatus::NullPointer, "out is null");
+        }
+        match doc.document.bounding_box() {
+            Some(b) => {
+                *out = b.into();
+                CaddyStatus::Ok
+            }
+            None => fail(CaddyStatus::NotFound, "document is empty"),
+        }
+    })
+}
+
+/// Length of a curve entity (line, circle, arc, polyline)
+///
+/// # Safety
+///
+/// `doc` must be a live handle; `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_entity_length(
+    doc: *const CaddyDocument,
+    index: usize,
+    out: *mut f64,
+) -> CaddyStatus {
+    guard(|| {
+        let doc = try_status!(doc_ref(doc));
+        if out.is_null() {
+            return fail(CaddyStatus::NullPointer, "out is null");
+        }
+        let entity = try_status!(entity_at(doc, index));
+        match entity_length(&entity.geometry) {
+            Some(length) => {
+                *out = length;
+                CaddyStatus::Ok
+            }
+            None => fail(
+                CaddyStatus::Unsupported,
+                format!("length is not defined for {}", entity.geometry.type_name()),
+            ),
+        }
+    })
+}
+
+/// Enclosed area of a closed entity (circle, ellipse, closed polyline)
+///
+/// # Safety
+///
+/// `doc` must be a live handle; `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_entity_area(
+    doc: *const CaddyDocument,
+    index: usize,
+    out: *mut f64,
+) -> CaddyStatus {
+    guard(|| {
+        let doc = try_status!(doc_ref(doc));
+        if out.is_null() {
+            return fail(CaddyStatus::NullPointer, "out is null");
+        }
+        let entity = try_status!(entity_at(doc, index));
+        match entity_area(&entity.geometry) {
+            Some(area) => {
+                *out = area;
+                CaddyStatus::Ok
+            }
+            None => fail(
+                CaddyStatus::Unsupported,
+                format!("area is not defined for {}", entity.geometry.type_name()),
+            ),
+        }
+    })
+}
+
+/// Index of the entity closest to `(x, y)` within `tolerance`
+///
+/// # Safety
+///
+/// `doc` must be a live handle; `out_index` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_document_pick(
+    doc: *const CaddyDocument,
+    x: f64,
+    y: f64,
+    tolerance: f64,
+    out_index: *mut usize,
+) -> CaddyStatus {
+    guard(|| {
+        let doc = try_status!(doc_ref(doc));
+        if out_index.is_null() {
+            return fail(CaddyStatus::NullPointer, "out_index is null");
+        }
+        let pick = Point2D::new(x, y);
+
+        let best = doc
+            .document
+            .entities
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.visible)
+            .filter_map(|(i, e)| entity_distance(&e.geometry, &pick).map(|d| (i, d)))
+            .filter(|(_, d)| *d <= tolerance)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        match best {
+            Some((index, _)) => {
+                *out_index = index;
+                CaddyStatus::Ok
+            }
+            None => fail(CaddyStatus::NotFound, "nothing within tolerance"),
+        }
+    })
+}
+
+fn p2(v: &Vec3) -> Point2D {
+    Point2D::new(v.x, v.y)
+}
+
+fn polyline_segments(geometry: &GeometryType) -> Option<Vec<LineSegment2D>> {
+    match geometry {
+        GeometryType::Polyline(p) if p.vertices.len() >= 2 => {
+            let n = p.vertices.len();
+            let count = if p.closed { n } else { n - 1 };
+            Some(
+                (0..count)
+                    .map(|i| {
+                        LineSegment2D::new(
+                            p2(&p.vertices[i].position),
+                            p2(&p.vertices[(i + 1) % n].position),
+                        )
+                    })
+                    .collect(),
+            )
+        }
+        _ => None,
+    }
+}
+
+fn arc_of(a: &Arc) -> Arc2D {
+    Arc2D::new(p2(&a.center), a.radius, a.start_angle, a.end_angle, true)
+}
+
+fn entity_length(geometry: &GeometryType) -> Option<f64> {
+    match geometry {
+        GeometryType::Line(l) => Some(LineSegment2D::new(p2(&l.start), p2(&l.end)).length()),
+        GeometryType::Circle(c) => Some(2.0 * std::f64::consts::PI * c.radius),
+        GeometryType::Arc(a) => Some(arc_of(a).length()),
+        GeometryType::Polyline(_) => {
+            polyline_segments(geometry).map(|segs| segs.iter().map(|s| s.length()).sum())
+        }
+        _ => None,
+    }
+}
+
+fn entity_area(geometry: &GeometryType) -> Option<f64> {
+    match geometry {
+        GeometryType::Circle(c) => Some(std::f64::consts::PI * c.radius * c.radius),
+        GeometryType::Ellipse(e) => Some(std::f64::consts::PI * e.major_axis * e.minor_axis),
+        GeometryType::Polyline(p) if p.closed && p.vertices.len() >= 3 => {
+            let polygon = Polygon2D::new(p.vertices.iter().map(|v| p2(&v.position)).collect());
+            Some(polygon.area())
+        }
+        _ => None,
+    }
+}
+
+fn entity_distance(geometry: &GeometryType, pick: &Point2D) -> Option<f64> {
+    match geometry {
+        GeometryType::Point(p) => Some(p2(&p.position).distance_to(pick)),
+        GeometryType::Line(l) => {
+            Some(LineSegment2D::new(p2(&l.start), p2(&l.end)).distance_to_point(pick))
+        }
+        GeometryType::Circle(c) => Some((p2(&c.center).distance_to(pick) - c.radius).abs()),
+        GeometryType::Arc(a) => {
+            let arc = arc_of(a);
+            let angle = (pick.y - a.center.y).atan2(pick.x - a.center.x);
+            if arc.contains_angle(angle) {
+                Some((p2(&a.center).distance_to(pick) - a.radius).abs())
+            } else {
+                Some(
+                    arc.start_point()
+                        .distance_to(pick)
+                        .min(arc.end_point().distance_to(pick)),
+                )
+            }
+        }
+        GeometryType::Polyline(_) => polyline_segments(geometry).and_then(|segs| {
+            segs.iter()
+                .map(|s| s.distance_to_point(pick))
+                .min_by(|a, b| a.total_cmp(b))
+        }),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// Editing and commands
+// ============================================================================
+
+/// Add a line on `layer` (null means the default layer "0")
+///
+/// `out_index` (optional) receives the new entity's index.
+///
+/// # Safety
+///
+/// `doc` must be a live handle; `layer` must be null or NUL-terminated;
+/// `out_index` must be null or valid.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_document_add_line(
+    doc: *mut CaddyDocument,
+    start: CaddyPoint3,
+    end: CaddyPoint3,
+    layer: *const c_char,
+    out_index: *mut usize,
+) -> CaddyStatus {
+    guard(|| {
+        let geometry = GeometryType::Line(Line {
+            start: start.into(),
+            end: end.into(),
+        });
+        add_entity(doc, geometry, layer, out_index)
+    })
+}
+
+/// Add a circle on `layer` (null means the default layer "0")
+///
+/// # Safety
+///
+/// Same requirements as `caddy_document_add_line`.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_document_add_circle(
+    doc: *mut CaddyDocument,
+    center: CaddyPoint3,
+    radius: f64,
+    layer: *const c_char,
+    out_index: *mut usize,
+) -> CaddyStatus {
+    guard(|| {
+        if radius.is_nan() || radius <= 0.0 {
+            return fail(CaddyStatus::InvalidArgument, "radius must be positive");
+        }
+        let geometry = GeometryType::Circle(Circle {
+            center: center.into(),
+            radius,
+            normal: Vec3::unit_z(),
+        });
+        add_entity(doc, geometry, layer, out_index)
+    })
+}
+
+unsafe fn add_entity(
+    doc: *mut CaddyDocument,
+    geometry: GeometryType,
+    layer: *const c_char,
+    out_index: *mut usize,
+) -> CaddyStatus {
+    let doc = try_status!(doc_mut(doc));
+    let layer = if layer.is_null() {
+        "0"
+    } else {
+        try_status!(str_arg(layer, "layer"))
+    };
+    if doc.document.get_layer(layer).is_none() {
+        return fail(CaddyStatus::NotFound, format!("layer '{}' does not exist", layer));
+    }
+
+    doc.document
+        .add_entity(Entity::new(geometry, layer.to_string()));
+    if !out_index.is_null() {
+        *out_index = doc.document.entities.len() - 1;
+    }
+    CaddyStatus::Ok
+}
+
+/// Remove the entity at `index`
+///
+/// # Safety
+///
+/// `doc` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_document_remove_entity(
+    doc: *mut CaddyDocument,
+    index: usize,
+) -> CaddyStatus {
+    guard(|| {
+        let doc = try_status!(doc_mut(doc));
+        let id = try_status!(entity_at(doc, index)).id;
+        doc.document.remove_entity(id);
+        doc.command_entities.retain(|_, uuid| *uuid != id);
+        CaddyStatus::Ok
+    })
+}
+
+/// Execute a command line (e.g. `"UNDO"`, `"CIRCLE"`) through the command processor
+///
+/// Entities created or removed by the command are reflected in the
+/// document's entity list when the call returns.
+///
+/// # Safety
+///
+/// `doc` must be a live handle; `command_line` must be NUL-terminated.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_document_execute(
+    doc: *mut CaddyDocument,
+    command_line: *const c_char,
+) -> CaddyStatus {
+    guard(|| {
+        let doc = try_status!(doc_mut(doc));
+        let line = try_status!(str_arg(command_line, "command_line"));
+        let result = doc.processor.execute(line, &mut doc.context);
+        doc.sync_command_entities();
+        match result {
+            Ok(()) => CaddyStatus::Ok,
+            Err(e) => fail(CaddyStatus::Command, e.to_string()),
+        }
+    })
+}
+
+/// Feed input (a point, distance, option...) to the active multi-step command
+///
+/// # Safety
+///
+/// `doc` must be a live handle; `input` must be NUL-terminated.
+#[no_mangle]
+pub unsafe extern "C" fn caddy_document_command_input(
+    doc: *mut CaddyDocument,
+    input: *const c_char,
+) -> CaddyStatus {
+    guard(|| {
+        let doc = try_status!(doc_mut(doc));
+        let input = try_status!(str_arg(input, "input"));
+        let result = doc.processor.process_input(input, &mut doc.context);
+        doc.sync_command_entities();
+        match result {
+            Ok(()) => CaddyStatus::Ok,
+            Err(e) => fail(CaddyStatus::Command, e.to_string()),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pt(x: f64, y: f64) -> CaddyPoint3 {
+        CaddyPoint3 { x, y, z: 0.0 }
+    }
+
+    #[test]
+    fn test_document_lifecycle_and_queries() {
+        unsafe {
+            let doc = caddy_document_new();
+            assert_eq!(caddy_document_entity_count(doc), 0);
+
+            let mut index = usize::MAX;
+            let status =
+                caddy_document_add_line(doc, pt(0.0, 0.0), pt(3.0, 4.0), std::ptr::null(), &mut index);
+            assert_eq!(status, CaddyStatus::Ok);
+            assert_eq!(index, 0);
+            assert_eq!(
+                caddy_document_add_circle(doc, pt(10.0, 0.0), 1.0, std::ptr::null(), std::ptr::null_mut()),
+                CaddyStatus::Ok
+            );
+            assert_eq!(caddy_document_entity_count(doc), 2);
+
+            let mut length = 0.0;
+            assert_eq!(caddy_entity_length(doc, 0, &mut length), CaddyStatus::Ok);
+            assert!((length - 5.0).abs() < 1e-9);
+
+            let mut area = 0.0;
+            assert_eq!(caddy_entity_area(doc, 0, &mut area), CaddyStatus::Unsupported);
+            assert_eq!(caddy_entity_area(doc, 1, &mut area), CaddyStatus::Ok);
+            assert!((area - std::f64::consts::PI).abs() < 1e-9);
+
+            let mut picked = usize::MAX;
+            assert_eq!(caddy_document_pick(doc, 11.05, 0.0, 0.1, &mut picked), CaddyStatus::Ok);
+            assert_eq!(picked, 1);
+
+            let mut info = std::mem::MaybeUninit::<CaddyEntityInfo>::uninit();
+            assert_eq!(caddy_document_entity_info(doc, 1, info.as_mut_ptr()), CaddyStatus::Ok);
+            let info = info.assume_init();
+            assert_eq!(info.kind, CaddyEntityKind::Circle);
+
+            let mut found = usize::MAX;
+            assert_eq!(caddy_document_find_entity(doc, info.id.as_ptr(), &mut found), CaddyStatus::Ok);
+            assert_eq!(found, 1);
+
+            assert_eq!(caddy_document_remove_entity(doc, 0), CaddyStatus::Ok);
+            assert_eq!(caddy_document_entity_count(doc), 1);
+
+            caddy_document_free(doc);
+        }
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        unsafe {
+            let doc = caddy_document_new();
+            let mut info = std::mem::MaybeUninit::<CaddyEntityInfo>::uninit();
+            assert_eq!(caddy_document_entity_info(doc, 5, info.as_mut_ptr()), CaddyStatus::NotFound);
+            let message = CStr::from_ptr(caddy_last_error()).to_str().unwrap();
+            assert!(message.contains("out of range"));
+
+            assert_eq!(
+                caddy_document_entity_info(std::ptr::null(), 0, info.as_mut_ptr()),
+                CaddyStatus::NullPointer
+            );
+
+            let layer = CString::new("missing").unwrap();
+            assert_eq!(
+                caddy_document_add_line(doc, pt(0.0, 0.0), pt(1.0, 0.0), layer.as_ptr(), std::ptr::null_mut()),
+                CaddyStatus::NotFound
+            );
+            caddy_document_free(doc);
+        }
+    }
+
+    #[test]
+    fn test_layer_buffer_copy() {
+        unsafe {
+            let doc = caddy_document_new();
+            caddy_document_add_line(doc, pt(0.0, 0.0), pt(1.0, 0.0), std::ptr::null(), std::ptr::null_mut());
+
+            let mut needed = 0usize;
+            assert_eq!(
+                caddy_document_entity_layer(doc, 0, std::ptr::null_mut(), 0, &mut needed),
+                CaddyStatus::BufferTooSmall
+            );
+            assert_eq!(needed, 2);
+
+            let mut buffer = [0 as c_char; 8];
+            assert_eq!(
+                caddy_document_entity_layer(doc, 0, buffer.as_mut_ptr(), buffer.len(), &mut needed),
+                CaddyStatus::Ok
+            );
+            assert_eq!(CStr::from_ptr(buffer.as_ptr()).to_str().unwrap(), "0");
+            caddy_document_free(doc);
+        }
+    }
+
+    #[test]
+    fn test_version_is_nul_terminated() {
+        let version = unsafe { CStr::from_ptr(caddy_version()) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    }
+}
@@ -7,7 +7,10 @@
 //! - Real-time metrics collection and aggregation
 //! - Time-series data storage with efficient compression
 //! - Performance profiling and tracing
+//! - Local viewport performance telemetry with a support-ticket report
 //! - Usage analytics and user behavior tracking
+//! - Optional time tracking per drawing and project, with weekly
+//!   timesheets and hooks for billing and ERP systems
 //! - Export to Prometheus, OpenTelemetry, and custom formats
 //! - Customizable reports and dashboards
 //!
@@ -44,6 +47,8 @@ pub mod export;
 pub mod performance;
 pub mod usage;
 pub mod reporting;
+pub mod telemetry;
+pub mod timetracking;
 
 // Re-exports for convenience
 pub use collector::{MetricsCollector, Metric, MetricType, MetricValue};
@@ -53,6 +58,8 @@ pub use export::{MetricsExporter, ExportFormat, PrometheusExporter, OpenTelemetr
 pub use performance::{PerformanceProfiler, ProfileSpan, ProfileReport};
 pub use usage::{UsageTracker, UsageEvent, UsageStats};
 pub use reporting::{ReportGenerator, ReportFormat, Report, ReportSection};
+pub use telemetry::{TelemetryCube, TelemetryConfig, DocumentProfile, ContentKind, SizeClass, Measure, Dimension, CubeQuery, CubeRow, MeasureSummary};
+pub use timetracking::{TimeTracker, TimeTrackingConfig, TimeTrackingError, TimeEntry, TimeAdjustment, TimeQuery, TrackedDocument, WeeklySummary, TimesheetHook, EntryChange};
 
 /// Analytics system errors
 #[derive(Debug, Error)]
@@ -113,6 +120,10 @@ pub struct AnalyticsConfig {
     /// Enable usage tracking
     pub enable_usage_tracking: bool,
 
+    /// Time tracking per drawing; off by default
+    #[serde(default)]
+    pub time_tracking: TimeTrackingConfig,
+
     /// Export endpoints
     pub export_endpoints: Vec<ExportEndpoint>,
 
@@ -130,6 +141,7 @@ impl Default for AnalyticsConfig {
             max_storage_bytes: 10 * 1024 * 1024 * 1024, // 10 GB
             enable_profiling: true,
             enable_usage_tracking: true,
+            time_tracking: TimeTrackingConfig::default(),
             export_endpoints: Vec::new(),
             storage_path: "./analytics_data".to_string(),
         }
@@ -165,6 +177,8 @@ pub struct AnalyticsSystem {
     profiler: Arc<PerformanceProfiler>,
     usage_tracker: Arc<UsageTracker>,
     report_generator: Arc<ReportGenerator>,
+    telemetry: Arc<TelemetryCube>,
+    time_tracker: Arc<TimeTracker>,
 }
 
 impl AnalyticsSystem {
@@ -183,6 +197,11 @@ impl AnalyticsSystem {
         let profiler = Arc::new(PerformanceProfiler::new(config.enable_profiling));
         let usage_tracker = Arc::new(UsageTracker::new(config.enable_usage_tracking));
         let report_generator = Arc::new(ReportGenerator::new());
+        let telemetry = Arc::new(TelemetryCube::new(TelemetryConfig {
+            enabled: config.enable_profiling,
+            ..Default::default()
+        }));
+        let time_tracker = Arc::new(TimeTracker::new(config.time_tracking.clone()));
 
         Ok(Self {
             config,
@@ -193,6 +212,8 @@ impl AnalyticsSystem {
             profiler,
             usage_tracker,
             report_generator,
+            telemetry,
+            time_tracker,
         })
     }
 
@@ -237,6 +258,16 @@ impl AnalyticsSystem {
         Arc::clone(&self.report_generator)
     }
 
+    /// Get the viewport performance telemetry cube
+    pub fn telemetry(&self) -> Arc<TelemetryCube> {
+        Arc::clone(&self.telemetry)
+    }
+
+    /// Get the time tracker
+    pub fn time_tracker(&self) -> Arc<TimeTracker> {
+        Arc::clone(&self.time_tracker)
+    }
+
     /// Query metrics for a time range
     pub async fn query_metrics(
         &self,
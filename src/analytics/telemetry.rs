@@ -0,0 +1,743 @@
+//! # Viewport Performance Telemetry
+//!
+//! A local, in-memory cube of frame times, command latencies and memory use,
+//! broken down by the characteristics of the open document.
+//!
+//! Samples never leave the machine and carry no document names or content,
+//! only coarse size classes. Each cell of the cube keeps a log-bucketed
+//! histogram rather than raw samples, so memory stays bounded however long a
+//! session runs, and percentiles are accurate to a few percent. Old periods
+//! are evicted once the configured retention is exceeded.
+//!
+//! [`TelemetryCube::performance_report`] summarises the cube as a [`Report`]
+//! that users can render with [`ReportGenerator`](super::ReportGenerator) and
+//! attach to support tickets.
+
+use super::aggregator::AggregationWindow;
+use super::reporting::{Report, ReportSection, ReportType, SectionType};
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Smallest value the histograms distinguish from zero
+const HISTOGRAM_MIN: f64 = 1e-3;
+
+/// Ratio between consecutive histogram bucket bounds (about 5% error)
+const HISTOGRAM_GROWTH: f64 = 1.1;
+
+/// Frame time above which the viewport drops below 30 fps
+const SLOW_FRAME_MS: f64 = 1000.0 / 30.0;
+
+/// Command latency users perceive as a stall
+const SLOW_COMMAND_MS: f64 = 1000.0;
+
+/// What a sample measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Measure {
+    /// Time to render one viewport frame, in milliseconds
+    FrameTime,
+    /// Time from issuing a command to its completion, in milliseconds
+    CommandLatency,
+    /// Resident memory of the process, in bytes
+    Memory,
+}
+
+impl Measure {
+    /// Unit the measure is recorded in
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Self::FrameTime | Self::CommandLatency => "ms",
+            Self::Memory => "bytes",
+        }
+    }
+}
+
+/// Coarse size class of a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SizeClass {
+    /// Under 1k entities or 10 layers
+    Tiny,
+    /// Under 10k entities or 50 layers
+    Small,
+    /// Under 100k entities or 200 layers
+    Medium,
+    /// Under 1M entities or 1000 layers
+    Large,
+    /// Anything bigger
+    Huge,
+}
+
+impl SizeClass {
+    /// Size class of a document with `count` entities
+    pub fn for_entities(count: usize) -> Self {
+        Self::classify(count, [1_000, 10_000, 100_000, 1_000_000])
+    }
+
+    /// Size class of a document with `count` layers
+    pub fn for_layers(count: usize) -> Self {
+        Self::classify(count, [10, 50, 200, 1_000])
+    }
+
+    fn classify(count: usize, bounds: [usize; 4]) -> Self {
+        match bounds.iter().position(|&bound| count < bound) {
+            Some(0) => Self::Tiny,
+            Some(1) => Self::Small,
+            Some(2) => Self::Medium,
+            Some(_) => Self::Large,
+            None => Self::Huge,
+        }
+    }
+
+    /// Human-readable label
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Tiny => "tiny",
+            Self::Small => "small",
+            Self::Medium => "medium",
+            Self::Large => "large",
+            Self::Huge => "huge",
+        }
+    }
+}
+
+/// Kind of content in a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ContentKind {
+    /// Flat 2D drafting
+    Drawing2D,
+    /// 3D solids and meshes only
+    Model3D,
+    /// Both
+    Mixed,
+}
+
+impl ContentKind {
+    /// Human-readable label
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Drawing2D => "2D",
+            Self::Model3D => "3D",
+            Self::Mixed => "mixed",
+        }
+    }
+}
+
+/// The characteristics of the open document a sample is filed under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DocumentProfile {
+    /// Number of entities
+    pub entity_count: usize,
+    /// Number of layers
+    pub layer_count: usize,
+    /// Kind of content
+    pub content: ContentKind,
+}
+
+impl DocumentProfile {
+    /// Create a document profile
+    pub fn new(entity_count: usize, layer_count: usize, content: ContentKind) -> Self {
+        Self {
+            entity_count,
+            layer_count,
+            content,
+        }
+    }
+}
+
+/// An axis of the cube
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Dimension {
+    /// Entity count size class
+    Entities,
+    /// Layer count size class
+    Layers,
+    /// Content kind
+    Content,
+    /// Command name, for command latencies
+    Command,
+    /// Start of the aggregation period
+    Period,
+}
+
+/// Telemetry cube configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    /// Enable recording
+    pub enabled: bool,
+
+    /// Width of one period of the time dimension
+    pub window: AggregationWindow,
+
+    /// Number of most recent periods kept
+    pub retention_periods: usize,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: AggregationWindow::Hour,
+            retention_periods: 24 * 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CellKey {
+    period: DateTime<Utc>,
+    measure: Measure,
+    entities: SizeClass,
+    layers: SizeClass,
+    content: ContentKind,
+    command: Option<String>,
+}
+
+impl CellKey {
+    fn label(&self, dimension: Dimension) -> String {
+        match dimension {
+            Dimension::Entities => self.entities.label().to_string(),
+            Dimension::Layers => self.layers.label().to_string(),
+            Dimension::Content => self.content.label().to_string(),
+            Dimension::Command => self.command.clone().unwrap_or_default(),
+            Dimension::Period => self.period.to_rfc3339(),
+        }
+    }
+}
+
+/// Log-bucketed histogram with exact count, sum and extremes
+#[derive(Debug, Clone, Default)]
+struct Cell {
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+    buckets: BTreeMap<i32, u64>,
+}
+
+impl Cell {
+    fn record(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.count += 1;
+        self.sum += value;
+        *self.buckets.entry(Self::bucket(value)).or_insert(0) += 1;
+    }
+
+    fn merge(&mut self, other: &Cell) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            self.min = other.min;
+            self.max = other.max;
+        } else {
+            self.min = self.min.min(other.min);
+            self.max = self.max.max(other.max);
+        }
+        self.count += other.count;
+        self.sum += other.sum;
+        for (&bucket, &count) in &other.buckets {
+            *self.buckets.entry(bucket).or_insert(0) += count;
+        }
+    }
+
+    fn bucket(value: f64) -> i32 {
+        if value <= HISTOGRAM_MIN {
+            -1
+        } else {
+            ((value / HISTOGRAM_MIN).ln() / HISTOGRAM_GROWTH.ln()).floor() as i32
+        }
+    }
+
+    /// Value at quantile `q`, the geometric middle of the bucket holding it
+    fn quantile(&self, q: f64) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        let rank = ((q * self.count as f64).ceil() as u64).clamp(1, self.count);
+        let mut seen = 0;
+        for (&bucket, &count) in &self.buckets {
+            seen += count;
+            if seen >= rank {
+                if bucket < 0 {
+                    return self.min;
+                }
+                let middle = HISTOGRAM_MIN * HISTOGRAM_GROWTH.powf(bucket as f64 + 0.5);
+                return middle.clamp(self.min, self.max);
+            }
+        }
+        self.max
+    }
+
+    fn summary(&self) -> MeasureSummary {
+        MeasureSummary {
+            count: self.count,
+            mean: if self.count > 0 { self.sum / self.count as f64 } else { 0.0 },
+            min: self.min,
+            max: self.max,
+            p50: self.quantile(0.5),
+            p95: self.quantile(0.95),
+            p99: self.quantile(0.99),
+        }
+    }
+}
+
+/// Statistics of one measure over a slice of the cube
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeasureSummary {
+    /// Number of samples
+    pub count: u64,
+    /// Mean value
+    pub mean: f64,
+    /// Smallest value
+    pub min: f64,
+    /// Largest value
+    pub max: f64,
+    /// Median
+    pub p50: f64,
+    /// 95th percentile
+    pub p95: f64,
+    /// 99th percentile
+    pub p99: f64,
+}
+
+/// A slice-and-group query over the cube
+#[derive(Debug, Clone)]
+pub struct CubeQuery {
+    measure: Measure,
+    group_by: Vec<Dimension>,
+    entities: Option<SizeClass>,
+    layers: Option<SizeClass>,
+    content: Option<ContentKind>,
+    command: Option<String>,
+    since: Option<DateTime<Utc>>,
+}
+
+impl CubeQuery {
+    /// Query all samples of `measure`, rolled up into one row
+    pub fn new(measure: Measure) -> Self {
+        Self {
+            measure,
+            group_by: Vec::new(),
+            entities: None,
+            layers: None,
+            content: None,
+            command: None,
+            since: None,
+        }
+    }
+
+    /// Break the result down along `dimension` as well
+    pub fn group_by(mut self, dimension: Dimension) -> Self {
+        if !self.group_by.contains(&dimension) {
+            self.group_by.push(dimension);
+        }
+        self
+    }
+
+    /// Only documents of this entity size class
+    pub fn entities(mut self, class: SizeClass) -> Self {
+        self.entities = Some(class);
+        self
+    }
+
+    /// Only documents of this layer size class
+    pub fn layers(mut self, class: SizeClass) -> Self {
+        self.layers = Some(class);
+        self
+    }
+
+    /// Only documents with this kind of content
+    pub fn content(mut self, kind: ContentKind) -> Self {
+        self.content = Some(kind);
+        self
+    }
+
+    /// Only latencies of this command
+    pub fn command(mut self, name: impl Into<String>) -> Self {
+        self.command = Some(name.into());
+        self
+    }
+
+    /// Only periods starting at or after `time`
+    pub fn since(mut self, time: DateTime<Utc>) -> Self {
+        self.since = Some(time);
+        self
+    }
+
+    fn matches(&self, key: &CellKey) -> bool {
+        key.measure == self.measure
+            && self.entities.is_none_or(|class| key.entities == class)
+            && self.layers.is_none_or(|class| key.layers == class)
+            && self.content.is_none_or(|kind| key.content == kind)
+            && self.command.as_ref().is_none_or(|name| key.command.as_ref() == Some(name))
+            && self.since.is_none_or(|time| key.period >= time)
+    }
+}
+
+/// One group of a query result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CubeRow {
+    /// The group's value along each grouped dimension, in query order
+    pub group: Vec<(Dimension, String)>,
+    /// Statistics of the group
+    pub summary: MeasureSummary,
+}
+
+/// In-memory performance telemetry cube
+pub struct TelemetryCube {
+    config: TelemetryConfig,
+    cells: RwLock<HashMap<CellKey, Cell>>,
+    periods: RwLock<BTreeSet<DateTime<Utc>>>,
+}
+
+impl TelemetryCube {
+    /// Create an empty cube
+    pub fn new(config: TelemetryConfig) -> Self {
+        Self {
+            config,
+            cells: RwLock::new(HashMap::new()),
+            periods: RwLock::new(BTreeSet::new()),
+        }
+    }
+
+    /// Record the render time of one frame
+    pub fn record_frame(&self, document: &DocumentProfile, frame_time_ms: f64) {
+        self.record_at(Utc::now(), Measure::FrameTime, document, None, frame_time_ms);
+    }
+
+    /// Record how long a command took
+    pub fn record_command(&self, document: &DocumentProfile, command: &str, latency_ms: f64) {
+        self.record_at(Utc::now(), Measure::CommandLatency, document, Some(command), latency_ms);
+    }
+
+    /// Record the process' memory use
+    pub fn record_memory(&self, document: &DocumentProfile, bytes: u64) {
+        self.record_at(Utc::now(), Measure::Memory, document, None, bytes as f64);
+    }
+
+    /// Record a sample taken at `time`
+    ///
+    /// Negative and non-finite values are dropped. The command name is only
+    /// kept for command latencies.
+    pub fn record_at(
+        &self,
+        time: DateTime<Utc>,
+        measure: Measure,
+        document: &DocumentProfile,
+        command: Option<&str>,
+        value: f64,
+    ) {
+        if !self.config.enabled || !value.is_finite() || value < 0.0 {
+            return;
+        }
+
+        let period = self.config.window.align(time);
+        let key = CellKey {
+            period,
+            measure,
+            entities: SizeClass::for_entities(document.entity_count),
+            layers: SizeClass::for_layers(document.layer_count),
+            content: document.content,
+            command: match measure {
+                Measure::CommandLatency => command.map(str::to_string),
+                _ => None,
+            },
+        };
+
+        let evicted = {
+            let mut periods = self.periods.write();
+            if periods.insert(period) {
+                let mut evicted = Vec::new();
+                while periods.len() > self.config.retention_periods.max(1) {
+                    evicted.extend(periods.pop_first());
+                }
+                evicted
+            } else {
+                Vec::new()
+            }
+        };
+
+        let mut cells = self.cells.write();
+        if !evicted.is_empty() {
+            cells.retain(|key, _| !evicted.contains(&key.period));
+        }
+        if self.periods.read().contains(&period) {
+            cells.entry(key).or_default().record(value);
+        }
+    }
+
+    /// Run a query, returning one row per group ordered by group labels
+    pub fn query(&self, query: &CubeQuery) -> Vec<CubeRow> {
+        let mut groups: BTreeMap<Vec<String>, Cell> = BTreeMap::new();
+        for (key, cell) in self.cells.read().iter().filter(|(key, _)| query.matches(key)) {
+            let labels = query.group_by.iter().map(|&dimension| key.label(dimension)).collect();
+            groups.entry(labels).or_default().merge(cell);
+        }
+
+        groups
+            .into_iter()
+            .map(|(labels, cell)| CubeRow {
+                group: query.group_by.iter().copied().zip(labels).collect(),
+                summary: cell.summary(),
+            })
+            .collect()
+    }
+
+    /// Statistics of all samples of `measure`, if there are any
+    pub fn summary(&self, measure: Measure) -> Option<MeasureSummary> {
+        self.query(&CubeQuery::new(measure))
+            .pop()
+            .map(|row| row.summary)
+            .filter(|summary| summary.count > 0)
+    }
+
+    /// Total number of samples held
+    pub fn sample_count(&self) -> u64 {
+        self.cells.read().values().map(|cell| cell.count).sum()
+    }
+
+    /// Number of occupied cells
+    pub fn cell_count(&self) -> usize {
+        self.cells.read().len()
+    }
+
+    /// Drop all samples
+    pub fn clear(&self) {
+        self.cells.write().clear();
+        self.periods.write().clear();
+    }
+
+    /// Summarise the cube as a report to attach to support tickets
+    pub fn performance_report(&self) -> Report {
+        let mut report = Report::new(ReportType::Performance, "Viewport Performance Report")
+            .with_description("Local performance telemetry by document size and content")
+            .with_metadata("version", env!("CARGO_PKG_VERSION"))
+            .with_metadata("os", std::env::consts::OS)
+            .with_metadata("arch", std::env::consts::ARCH);
+
+        {
+            let periods = self.periods.read();
+            if let (Some(&first), Some(&last)) = (periods.first(), periods.last()) {
+                report = report.with_time_range(first, last + self.config.window.duration());
+            }
+        }
+
+        let frames = self.query(
+            &CubeQuery::new(Measure::FrameTime)
+                .group_by(Dimension::Entities)
+                .group_by(Dimension::Content),
+        );
+        let mut commands = self.query(&CubeQuery::new(Measure::CommandLatency).group_by(Dimension::Command));
+        commands.sort_by(|a, b| b.summary.p95.total_cmp(&a.summary.p95));
+        commands.truncate(10);
+        let memory = self.query(&CubeQuery::new(Measure::Memory).group_by(Dimension::Entities));
+
+        let environment = ReportSection {
+            title: "Environment".to_string(),
+            content: format!(
+                "Version: {}\nPlatform: {} ({})\nSamples: {}",
+                env!("CARGO_PKG_VERSION"),
+                std::env::consts::OS,
+                std::env::consts::ARCH,
+                self.sample_count()
+            ),
+            data: None,
+            section_type: SectionType::KeyValue,
+            subsections: Vec::new(),
+        };
+
+        let frame_lines = frames
+            .iter()
+            .map(|row| {
+                format!(
+                    "{}: {} frames, p50={:.1}ms, p95={:.1}ms ({:.0} fps), max={:.1}ms",
+                    Self::group_label(row),
+                    row.summary.count,
+                    row.summary.p50,
+                    row.summary.p95,
+                    1000.0 / row.summary.p95.max(HISTOGRAM_MIN),
+                    row.summary.max
+                )
+            })
+            .collect::<Vec<_>>();
+        let frame_section = Self::table_section("Frame Times by Document", frame_lines, &frames);
+
+        let command_lines = commands
+            .iter()
+            .map(|row| {
+                format!(
+                    "{}: {} runs, p50={:.1}ms, p95={:.1}ms, max={:.1}ms",
+                    Self::group_label(row),
+                    row.summary.count,
+                    row.summary.p50,
+                    row.summary.p95,
+                    row.summary.max
+                )
+            })
+            .collect::<Vec<_>>();
+        let command_section = Self::table_section("Slowest Commands (Top 10 by p95)", command_lines, &commands);
+
+        let memory_lines = memory
+            .iter()
+            .map(|row| {
+                format!(
+                    "{}: p50={:.0} MiB, max={:.0} MiB",
+                    Self::group_label(row),
+                    row.summary.p50 / (1024.0 * 1024.0),
+                    row.summary.max / (1024.0 * 1024.0)
+                )
+            })
+            .collect::<Vec<_>>();
+        let memory_section = Self::table_section("Memory by Document Size", memory_lines, &memory);
+
+        let mut findings = Vec::new();
+        for row in frames.iter().filter(|row| row.summary.p95 > SLOW_FRAME_MS) {
+            findings.push(format!(
+                "Viewport drops below 30 fps for {} documents (p95 {:.1}ms)",
+                Self::group_label(row),
+                row.summary.p95
+            ));
+        }
+        for row in commands.iter().filter(|row| row.summary.p95 > SLOW_COMMAND_MS) {
+            findings.push(format!(
+                "Command {} stalls for over a second (p95 {:.0}ms)",
+                Self::group_label(row),
+                row.summary.p95
+            ));
+        }
+        let findings_section = ReportSection {
+            title: "Findings".to_string(),
+            content: if findings.is_empty() {
+                "No performance problems detected.".to_string()
+            } else {
+                findings.join("\n")
+            },
+            data: None,
+            section_type: SectionType::List,
+            subsections: Vec::new(),
+        };
+
+        report
+            .add_section(environment)
+            .add_section(frame_section)
+            .add_section(command_section)
+            .add_section(memory_section)
+            .add_section(findings_section)
+    }
+
+    fn group_label(row: &CubeRow) -> String {
+        row.group
+            .iter()
+            .map(|(_, label)| label.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn table_section(title: &str, lines: Vec<String>, rows: &[CubeRow]) -> ReportSection {
+        ReportSection {
+            title: title.to_string(),
+            content: if lines.is_empty() {
+                "No samples recorded".to_string()
+            } else {
+                lines.join("\n")
+            },
+            data: serde_json::to_value(rows).ok(),
+            section_type: SectionType::Table,
+            subsections: Vec::new(),
+        }
+    }
+}
+
+impl Default for TelemetryCube {
+    fn default() -> Self {
+        Self::new(TelemetryConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_query_groups_by_document_size() {
+        let cube = TelemetryCube::default();
+        let small = DocumentProfile::new(500, 4, ContentKind::Drawing2D);
+        let large = DocumentProfile::new(250_000, 40, ContentKind::Drawing2D);
+
+        for i in 1..=100 {
+            cube.record_frame(&small, i as f64 / 10.0);
+            cube.record_frame(&large, i as f64);
+        }
+        cube.record_memory(&large, 512 * 1024 * 1024);
+
+        let rows = cube.query(&CubeQuery::new(Measure::FrameTime).group_by(Dimension::Entities));
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].group, vec![(Dimension::Entities, "large".to_string())]);
+
+        let large_frames = &rows[0].summary;
+        assert_eq!(large_frames.count, 100);
+        assert!((large_frames.mean - 50.5).abs() < 1e-9);
+        assert_eq!(large_frames.max, 100.0);
+        assert!((large_frames.p50 - 50.0).abs() / 50.0 < 0.06);
+        assert!((large_frames.p95 - 95.0).abs() / 95.0 < 0.06);
+
+        let tiny = cube.query(&CubeQuery::new(Measure::FrameTime).entities(SizeClass::Tiny));
+        assert_eq!(tiny.len(), 1);
+        assert_eq!(tiny[0].summary.max, 10.0);
+
+        assert_eq!(cube.summary(Measure::Memory).unwrap().count, 1);
+        assert!(cube.summary(Measure::CommandLatency).is_none());
+    }
+
+    #[test]
+    fn test_retention_evicts_old_periods() {
+        let cube = TelemetryCube::new(TelemetryConfig {
+            retention_periods: 2,
+            ..Default::default()
+        });
+        let document = DocumentProfile::new(10, 1, ContentKind::Model3D);
+        let start = Utc::now();
+
+        for hour in 0..5 {
+            let time = start + Duration::hours(hour);
+            cube.record_at(time, Measure::CommandLatency, &document, Some("EXTRUDE"), 40.0);
+        }
+        // Samples older than the retained periods are dropped
+        cube.record_at(start, Measure::CommandLatency, &document, Some("EXTRUDE"), 40.0);
+
+        assert_eq!(cube.sample_count(), 2);
+        let rows = cube.query(&CubeQuery::new(Measure::CommandLatency).group_by(Dimension::Period));
+        assert_eq!(rows.len(), 2);
+        assert_eq!(cube.query(&CubeQuery::new(Measure::CommandLatency).command("EXTRUDE"))[0].summary.count, 2);
+    }
+
+    #[test]
+    fn test_performance_report() {
+        let cube = TelemetryCube::default();
+        let document = DocumentProfile::new(2_000_000, 300, ContentKind::Mixed);
+        for _ in 0..20 {
+            cube.record_frame(&document, 50.0);
+            cube.record_command(&document, "BOOLEAN_UNION", 2500.0);
+            cube.record_command(&document, "LINE", 2.0);
+        }
+
+        let report = cube.performance_report();
+        assert_eq!(report.report_type, ReportType::Performance);
+        assert_eq!(report.sections.len(), 5);
+        assert!(report.sections[1].content.contains("huge mixed: 20 frames"));
+        assert!(report.sections[2].content.starts_with("BOOLEAN_UNION"));
+
+        let findings = &report.sections[4].content;
+        assert!(findings.contains("below 30 fps for huge mixed"));
+        assert!(findings.contains("BOOLEAN_UNION"));
+        assert!(!findings.contains("LINE"));
+    }
+}
@@ -0,0 +1,868 @@
+//! # Time Tracking
+//!
+//! Optional tracking of active editing time, attributed to the drawing being
+//! edited and to its project. It is off unless enabled in the configuration.
+//!
+//! Time is built from activity signals. Every edit, command or view change
+//! in a drawing extends the user's current segment. A gap longer than the
+//! idle timeout ends the segment at the last activity, so time away from
+//! the keyboard is never counted, and switching to another drawing ends the
+//! segment and starts a new one. Closed segments become [`TimeEntry`]s that
+//! users can correct, remove or add to by hand; corrected entries keep the
+//! tracked times they replaced.
+//!
+//! [`TimeTracker::weekly_summary`] breaks a week down by user, project and
+//! drawing, day by day (UTC). Every entry change and every closed week is
+//! passed to the registered [`TimesheetHook`]s, which is where billing and
+//! ERP integrations plug in.
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc, Weekday};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Errors from the time tracker
+#[derive(Debug, Error)]
+pub enum TimeTrackingError {
+    #[error("Time tracking is disabled")]
+    Disabled,
+
+    #[error("Time entry not found: {0}")]
+    EntryNotFound(Uuid),
+
+    #[error("Invalid time range: {start} to {end}")]
+    InvalidRange { start: DateTime<Utc>, end: DateTime<Utc> },
+
+    #[error("Overlaps time entry {0}")]
+    Overlap(Uuid),
+}
+
+/// Time tracking configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeTrackingConfig {
+    /// Whether time is tracked at all
+    pub enabled: bool,
+
+    /// Seconds without activity after which the user counts as idle
+    pub idle_timeout_secs: i64,
+
+    /// Tracked segments shorter than this are dropped, in seconds
+    pub min_entry_secs: i64,
+
+    /// First day of the week for weekly summaries
+    pub week_start: Weekday,
+}
+
+impl Default for TimeTrackingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_timeout_secs: 300,
+            min_entry_secs: 30,
+            week_start: Weekday::Mon,
+        }
+    }
+}
+
+/// Drawing that time is attributed to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackedDocument {
+    pub document_id: String,
+    pub document_name: Option<String>,
+    pub project_id: Option<String>,
+}
+
+impl TrackedDocument {
+    pub fn new(document_id: impl Into<String>) -> Self {
+        Self {
+            document_id: document_id.into(),
+            document_name: None,
+            project_id: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.document_name = Some(name.into());
+        self
+    }
+
+    pub fn with_project(mut self, project_id: impl Into<String>) -> Self {
+        self.project_id = Some(project_id.into());
+        self
+    }
+}
+
+/// How an entry came about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntrySource {
+    /// Built from editing activity
+    Tracked,
+    /// Entered by hand
+    Manual,
+}
+
+/// Block of time spent on one drawing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeEntry {
+    pub id: Uuid,
+    pub user_id: String,
+    #[serde(flatten)]
+    pub document: TrackedDocument,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub source: EntrySource,
+    pub billable: bool,
+    pub note: Option<String>,
+
+    /// Tracked start and end before the entry was corrected
+    pub original: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    pub adjusted_by: Option<String>,
+    pub adjusted_at: Option<DateTime<Utc>>,
+}
+
+impl TimeEntry {
+    fn new(
+        user_id: &str,
+        document: TrackedDocument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        source: EntrySource,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id: user_id.to_string(),
+            document,
+            start,
+            end,
+            source,
+            billable: true,
+            note: None,
+            original: None,
+            adjusted_by: None,
+            adjusted_at: None,
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    /// Whether the entry was corrected by hand
+    pub fn is_adjusted(&self) -> bool {
+        self.adjusted_at.is_some()
+    }
+}
+
+/// Correction to an entry; unset fields are left alone
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeAdjustment {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub billable: Option<bool>,
+    pub note: Option<String>,
+}
+
+/// Filter for entries; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeQuery {
+    pub user_id: Option<String>,
+    pub project_id: Option<String>,
+    pub document_id: Option<String>,
+
+    /// Entries ending after this
+    pub since: Option<DateTime<Utc>>,
+
+    /// Entries starting before this
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl TimeQuery {
+    fn matches(&self, entry: &TimeEntry) -> bool {
+        self.user_id.as_ref().is_none_or(|u| &entry.user_id == u)
+            && self
+                .project_id
+                .as_ref()
+                .is_none_or(|p| entry.document.project_id.as_ref() == Some(p))
+            && self
+                .document_id
+                .as_ref()
+                .is_none_or(|d| &entry.document.document_id == d)
+            && self.since.is_none_or(|since| entry.end > since)
+            && self.until.is_none_or(|until| entry.start < until)
+    }
+}
+
+/// Segment being tracked right now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveSegment {
+    pub user_id: String,
+    pub document: TrackedDocument,
+    pub started: DateTime<Utc>,
+    pub last_activity: DateTime<Utc>,
+}
+
+/// One user's time on one drawing over a week
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyRow {
+    pub user_id: String,
+    pub project_id: Option<String>,
+    pub document_id: String,
+    pub document_name: Option<String>,
+
+    /// Seconds per day, from the first day of the week
+    pub daily_secs: [i64; 7],
+    pub total_secs: i64,
+    pub billable_secs: i64,
+}
+
+/// Time per user, project and drawing over one week
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklySummary {
+    pub week_start: DateTime<Utc>,
+    pub week_end: DateTime<Utc>,
+
+    /// Ordered by user, project, then drawing
+    pub rows: Vec<WeeklyRow>,
+    pub total_secs: i64,
+    pub billable_secs: i64,
+}
+
+impl WeeklySummary {
+    /// Total seconds per project, unassigned time under `None`
+    pub fn project_totals(&self) -> BTreeMap<Option<String>, i64> {
+        let mut totals = BTreeMap::new();
+        for row in &self.rows {
+            *totals.entry(row.project_id.clone()).or_default() += row.total_secs;
+        }
+        totals
+    }
+
+    /// Timesheet CSV (RFC 4180) with hours per day
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("user_id,project_id,document_id,document_name");
+        for day in 0..7 {
+            out.push_str(&format!(
+                ",{}",
+                (self.week_start + Duration::days(day)).format("%Y-%m-%d")
+            ));
+        }
+        out.push_str(",total_hours,billable_hours\r\n");
+
+        for row in &self.rows {
+            let mut fields = vec![
+                row.user_id.clone(),
+                row.project_id.clone().unwrap_or_default(),
+                row.document_id.clone(),
+                row.document_name.clone().unwrap_or_default(),
+            ];
+            fields.extend(row.daily_secs.iter().map(|&secs| hours(secs)));
+            fields.push(hours(row.total_secs));
+            fields.push(hours(row.billable_secs));
+            let fields: Vec<String> = fields.iter().map(|f| quote(f)).collect();
+            out.push_str(&fields.join(","));
+            out.push_str("\r\n");
+        }
+        out
+    }
+}
+
+/// What happened to an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryChange {
+    Created,
+    Adjusted,
+    Removed,
+}
+
+/// Receives time entries as they change, for billing and ERP integrations
+///
+/// Hooks are called synchronously while the tracker records the change, so
+/// they should hand work off (to a queue or task) rather than block.
+pub trait TimesheetHook: Send + Sync {
+    /// Name of the integration, for logs
+    fn name(&self) -> &str;
+
+    /// An entry was created, corrected or removed
+    fn on_entry(&self, _entry: &TimeEntry, _change: EntryChange) {}
+
+    /// A week was closed with [`TimeTracker::close_week`]
+    fn on_week_closed(&self, _summary: &WeeklySummary) {}
+}
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    active: HashMap<String, ActiveSegment>,
+    entries: Vec<TimeEntry>,
+}
+
+/// Attributes active editing time to drawings and projects
+pub struct TimeTracker {
+    config: RwLock<TimeTrackingConfig>,
+    state: RwLock<TrackerState>,
+    hooks: RwLock<Vec<Arc<dyn TimesheetHook>>>,
+}
+
+impl TimeTracker {
+    pub fn new(config: TimeTrackingConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            state: RwLock::new(TrackerState::default()),
+            hooks: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub fn config(&self) -> TimeTrackingConfig {
+        self.config.read().clone()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.read().enabled
+    }
+
+    /// Turn tracking on or off; turning it off ends every active segment
+    pub fn set_enabled(&self, enabled: bool, at: DateTime<Utc>) -> Vec<TimeEntry> {
+        self.config.write().enabled = enabled;
+        if enabled {
+            return Vec::new();
+        }
+        let users: Vec<String> = self.state.read().active.keys().cloned().collect();
+        users.into_iter().filter_map(|user| self.pause(&user, at)).collect()
+    }
+
+    /// Register an integration hook
+    pub fn add_hook(&self, hook: Arc<dyn TimesheetHook>) {
+        log::info!("Time tracking hook registered: {}", hook.name());
+        self.hooks.write().push(hook);
+    }
+
+    /// Record editing activity by a user in a drawing
+    ///
+    /// Returns the entry closed by this activity, if it ended an idle
+    /// segment or switched drawings.
+    pub fn record_activity(&self, user_id: &str, document: &TrackedDocument, at: DateTime<Utc>) -> Option<TimeEntry> {
+        let config = self.config();
+        if !config.enabled {
+            return None;
+        }
+
+        let closed = {
+            let mut state = self.state.write();
+            let closed = match state.active.get_mut(user_id) {
+                Some(segment) => {
+                    let idle = at - segment.last_activity > Duration::seconds(config.idle_timeout_secs);
+                    if !idle && &segment.document == document {
+                        segment.last_activity = segment.last_activity.max(at);
+                        return None;
+                    }
+                    // Idle time is dropped; a switch counts up to the switch
+                    let end = if idle {
+                        segment.last_activity
+                    } else {
+                        at.max(segment.last_activity)
+                    };
+                    state.active.remove(user_id).and_then(|s| close(s, end, &config))
+                }
+                None => None,
+            };
+
+            state.active.insert(
+                user_id.to_string(),
+                ActiveSegment {
+                    user_id: user_id.to_string(),
+                    document: document.clone(),
+                    started: at,
+                    last_activity: at,
+                },
+            );
+            if let Some(entry) = &closed {
+                state.entries.push(entry.clone());
+            }
+            closed
+        };
+
+        if let Some(entry) = &closed {
+            self.notify(entry, EntryChange::Created);
+        }
+        closed
+    }
+
+    /// End a user's segment now, e.g. when the window loses focus or the
+    /// drawing is closed
+    pub fn pause(&self, user_id: &str, at: DateTime<Utc>) -> Option<TimeEntry> {
+        let config = self.config();
+        let entry = {
+            let mut state = self.state.write();
+            let segment = state.active.remove(user_id)?;
+            let idle = at - segment.last_activity > Duration::seconds(config.idle_timeout_secs);
+            let end = if idle {
+                segment.last_activity
+            } else {
+                at.max(segment.last_activity)
+            };
+            let entry = close(segment, end, &config)?;
+            state.entries.push(entry.clone());
+            entry
+        };
+        self.notify(&entry, EntryChange::Created);
+        Some(entry)
+    }
+
+    /// End every segment idle for longer than the timeout; call
+    /// periodically
+    pub fn close_idle(&self, now: DateTime<Utc>) -> Vec<TimeEntry> {
+        let timeout = Duration::seconds(self.config.read().idle_timeout_secs);
+        let idle: Vec<String> = self
+            .state
+            .read()
+            .active
+            .values()
+            .filter(|s| now - s.last_activity > timeout)
+            .map(|s| s.user_id.clone())
+            .collect();
+        idle.into_iter().filter_map(|user| self.pause(&user, now)).collect()
+    }
+
+    /// Segment a user is in right now, for the status bar
+    pub fn active_segment(&self, user_id: &str) -> Option<ActiveSegment> {
+        self.state.read().active.get(user_id).cloned()
+    }
+
+    /// Add time the tracker missed
+    pub fn add_manual_entry(
+        &self,
+        user_id: &str,
+        document: TrackedDocument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        note: Option<String>,
+    ) -> Result<TimeEntry, TimeTrackingError> {
+        if !self.is_enabled() {
+            return Err(TimeTrackingError::Disabled);
+        }
+        if end <= start {
+            return Err(TimeTrackingError::InvalidRange { start, end });
+        }
+
+        let entry = {
+            let mut state = self.state.write();
+            check_overlap(&state.entries, user_id, start, end, None)?;
+            let mut entry = TimeEntry::new(user_id, document, start, end, EntrySource::Manual);
+            entry.note = note;
+            state.entries.push(entry.clone());
+            entry
+        };
+        self.notify(&entry, EntryChange::Created);
+        Ok(entry)
+    }
+
+    /// Correct an entry
+    pub fn adjust_entry(&self, id: Uuid, adjustment: TimeAdjustment, by: &str) -> Result<TimeEntry, TimeTrackingError> {
+        let entry = {
+            let mut state = self.state.write();
+            let current = state
+                .entries
+                .iter()
+                .find(|e| e.id == id)
+                .ok_or(TimeTrackingError::EntryNotFound(id))?;
+            let start = adjustment.start.unwrap_or(current.start);
+            let end = adjustment.end.unwrap_or(current.end);
+            if end <= start {
+                return Err(TimeTrackingError::InvalidRange { start, end });
+            }
+            let user_id = current.user_id.clone();
+            check_overlap(&state.entries, &user_id, start, end, Some(id))?;
+
+            let entry = state
+                .entries
+                .iter_mut()
+                .find(|e| e.id == id)
+                .expect("entry was found above");
+            if (start, end) != (entry.start, entry.end) && entry.original.is_none() {
+                entry.original = Some((entry.start, entry.end));
+            }
+            entry.start = start;
+            entry.end = end;
+            if let Some(billable) = adjustment.billable {
+                entry.billable = billable;
+            }
+            if adjustment.note.is_some() {
+                entry.note = adjustment.note;
+            }
+            entry.adjusted_by = Some(by.to_string());
+            entry.adjusted_at = Some(Utc::now());
+            entry.clone()
+        };
+        self.notify(&entry, EntryChange::Adjusted);
+        Ok(entry)
+    }
+
+    /// Remove an entry
+    pub fn remove_entry(&self, id: Uuid) -> Result<TimeEntry, TimeTrackingError> {
+        let entry = {
+            let mut state = self.state.write();
+            let index = state
+                .entries
+                .iter()
+                .position(|e| e.id == id)
+                .ok_or(TimeTrackingError::EntryNotFound(id))?;
+            state.entries.remove(index)
+        };
+        self.notify(&entry, EntryChange::Removed);
+        Ok(entry)
+    }
+
+    pub fn get_entry(&self, id: Uuid) -> Option<TimeEntry> {
+        self.state.read().entries.iter().find(|e| e.id == id).cloned()
+    }
+
+    /// Entries matching a query, oldest first
+    pub fn entries(&self, query: &TimeQuery) -> Vec<TimeEntry> {
+        let mut entries: Vec<TimeEntry> = self
+            .state
+            .read()
+            .entries
+            .iter()
+            .filter(|e| query.matches(e))
+            .cloned()
+            .collect();
+        entries.sort_by_key(|e| e.start);
+        entries
+    }
+
+    /// Start of the week containing `at`
+    pub fn week_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let first = self.config.read().week_start;
+        let offset = (7 + at.weekday().num_days_from_monday() - first.num_days_from_monday()) % 7;
+        let day = at.date_naive() - Duration::days(offset as i64);
+        Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0).expect("midnight is valid"))
+    }
+
+    /// Time per user, project and drawing in the week containing `week_of`,
+    /// optionally for one user
+    pub fn weekly_summary(&self, week_of: DateTime<Utc>, user_id: Option<&str>) -> WeeklySummary {
+        let week_start = self.week_start(week_of);
+        let week_end = week_start + Duration::days(7);
+        let query = TimeQuery {
+            user_id: user_id.map(str::to_string),
+            since: Some(week_start),
+            until: Some(week_end),
+            ..Default::default()
+        };
+
+        let mut rows: BTreeMap<(String, Option<String>, String), WeeklyRow> = BTreeMap::new();
+        for entry in self.entries(&query) {
+            let key = (
+                entry.user_id.clone(),
+                entry.document.project_id.clone(),
+                entry.document.document_id.clone(),
+            );
+            let row = rows.entry(key).or_insert_with(|| WeeklyRow {
+                user_id: entry.user_id.clone(),
+                project_id: entry.document.project_id.clone(),
+                document_id: entry.document.document_id.clone(),
+                document_name: None,
+                daily_secs: [0; 7],
+                total_secs: 0,
+                billable_secs: 0,
+            });
+            if entry.document.document_name.is_some() {
+                row.document_name = entry.document.document_name.clone();
+            }
+
+            // Split across midnights so each day gets its share
+            for day in 0..7 {
+                let day_start = week_start + Duration::days(day);
+                let day_end = day_start + Duration::days(1);
+                let secs = (entry.end.min(day_end) - entry.start.max(day_start))
+                    .num_seconds()
+                    .max(0);
+                row.daily_secs[day as usize] += secs;
+                row.total_secs += secs;
+                if entry.billable {
+                    row.billable_secs += secs;
+                }
+            }
+        }
+
+        let rows: Vec<WeeklyRow> = rows.into_values().filter(|r| r.total_secs > 0).collect();
+        WeeklySummary {
+            week_start,
+            week_end,
+            total_secs: rows.iter().map(|r| r.total_secs).sum(),
+            billable_secs: rows.iter().map(|r| r.billable_secs).sum(),
+            rows,
+        }
+    }
+
+    /// Summarise a finished week for every user and hand it to the hooks
+    pub fn close_week(&self, week_of: DateTime<Utc>) -> WeeklySummary {
+        let summary = self.weekly_summary(week_of, None);
+        for hook in self.hooks.read().iter() {
+            hook.on_week_closed(&summary);
+        }
+        summary
+    }
+
+    fn notify(&self, entry: &TimeEntry, change: EntryChange) {
+        for hook in self.hooks.read().iter() {
+            hook.on_entry(entry, change);
+        }
+    }
+}
+
+impl Default for TimeTracker {
+    fn default() -> Self {
+        Self::new(TimeTrackingConfig::default())
+    }
+}
+
+impl std::fmt::Debug for TimeTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimeTracker")
+            .field("config", &*self.config.read())
+            .field("entries", &self.state.read().entries.len())
+            .field("hooks", &self.hooks.read().len())
+            .finish()
+    }
+}
+
+/// Turn a segment into an entry, unless it is too short to count
+fn close(segment: ActiveSegment, end: DateTime<Utc>, config: &TimeTrackingConfig) -> Option<TimeEntry> {
+    let length = end - segment.started;
+    if length <= Duration::zero() || length < Duration::seconds(config.min_entry_secs) {
+        return None;
+    }
+    Some(TimeEntry::new(
+        &segment.user_id,
+        segment.document,
+        segment.started,
+        end,
+        EntrySource::Tracked,
+    ))
+}
+
+fn check_overlap(
+    entries: &[TimeEntry],
+    user_id: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    except: Option<Uuid>,
+) -> Result<(), TimeTrackingError> {
+    match entries
+        .iter()
+        .find(|e| e.user_id == user_id && Some(e.id) != except && e.start < end && start < e.end)
+    {
+        Some(existing) => Err(TimeTrackingError::Overlap(existing.id)),
+        None => Ok(()),
+    }
+}
+
+/// Entries as CSV (RFC 4180)
+pub fn entries_csv(entries: &[TimeEntry]) -> String {
+    let mut out = String::from(
+        "id,user_id,project_id,document_id,document_name,start,end,hours,source,billable,adjusted,note\r\n",
+    );
+    for e in entries {
+        let fields = [
+            e.id.to_string(),
+            e.user_id.clone(),
+            e.document.project_id.clone().unwrap_or_default(),
+            e.document.document_id.clone(),
+            e.document.document_name.clone().unwrap_or_default(),
+            e.start.to_rfc3339(),
+            e.end.to_rfc3339(),
+            hours(e.duration().num_seconds()),
+            match e.source {
+                EntrySource::Tracked => "tracked".to_string(),
+                EntrySource::Manual => "manual".to_string(),
+            },
+            e.billable.to_string(),
+            e.is_adjusted().to_string(),
+            e.note.clone().unwrap_or_default(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|f| quote(f)).collect();
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn hours(secs: i64) -> String {
+    format!("{:.2}", secs as f64 / 3600.0)
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn at(day: u32, hour: u32, min: u32) -> DateTime<Utc> {
+        // 2026-10-12 is a Monday
+        Utc.with_ymd_and_hms(2026, 10, 11 + day, hour, min, 0).unwrap()
+    }
+
+    fn tracker() -> TimeTracker {
+        TimeTracker::new(TimeTrackingConfig {
+            enabled: true,
+            ..Default::default()
+        })
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<(EntryChange, i64)>>, Mutex<usize>);
+
+    impl TimesheetHook for Recorder {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+
+        fn on_entry(&self, entry: &TimeEntry, change: EntryChange) {
+            self.0.lock().unwrap().push((change, entry.duration().num_minutes()));
+        }
+
+        fn on_week_closed(&self, _summary: &WeeklySummary) {
+            *self.1.lock().unwrap() += 1;
+        }
+    }
+
+    #[test]
+    fn test_idle_gaps_and_switches_split_entries() {
+        let tracker = tracker();
+        let recorder = Arc::new(Recorder::default());
+        tracker.add_hook(recorder.clone());
+        let plan = TrackedDocument::new("plan").with_project("tower");
+        let section = TrackedDocument::new("section").with_project("tower");
+
+        assert!(TimeTracker::default()
+            .record_activity("ana", &plan, at(1, 9, 0))
+            .is_none());
+
+        tracker.record_activity("ana", &plan, at(1, 9, 0));
+        tracker.record_activity("ana", &plan, at(1, 9, 4));
+        assert_eq!(tracker.active_segment("ana").unwrap().started, at(1, 9, 0));
+
+        // Twenty idle minutes end the entry at the last activity
+        let entry = tracker.record_activity("ana", &plan, at(1, 9, 24)).unwrap();
+        assert_eq!((entry.start, entry.end), (at(1, 9, 0), at(1, 9, 4)));
+
+        tracker.record_activity("ana", &plan, at(1, 9, 28));
+        let entry = tracker.record_activity("ana", &section, at(1, 9, 30)).unwrap();
+        assert_eq!(entry.document.document_id, "plan");
+        assert_eq!(entry.duration(), Duration::minutes(6));
+
+        // The last segment is still open until the idle sweep
+        tracker.record_activity("ana", &section, at(1, 9, 33));
+        assert!(tracker.close_idle(at(1, 9, 35)).is_empty());
+        assert_eq!(tracker.close_idle(at(1, 10, 0)).len(), 1);
+        assert!(tracker.active_segment("ana").is_none());
+
+        let entries = tracker.entries(&TimeQuery {
+            project_id: Some("tower".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(entries.len(), 3);
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![
+                (EntryChange::Created, 4),
+                (EntryChange::Created, 6),
+                (EntryChange::Created, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_manual_adjustment_and_weekly_summary() {
+        let tracker = tracker();
+        let recorder = Arc::new(Recorder::default());
+        tracker.add_hook(recorder.clone());
+        let plan = TrackedDocument::new("plan")
+            .with_name("Level 1, plan")
+            .with_project("tower");
+
+        // Late-night work is split across the two days
+        let entry = tracker
+            .add_manual_entry(
+                "ana",
+                plan.clone(),
+                at(1, 23, 0),
+                at(2, 1, 0),
+                Some("site visit".to_string()),
+            )
+            .unwrap();
+        assert!(matches!(
+            tracker.add_manual_entry("ana", plan.clone(), at(2, 0, 30), at(2, 2, 0), None),
+            Err(TimeTrackingError::Overlap(id)) if id == entry.id
+        ));
+        assert!(tracker
+            .add_manual_entry("ana", plan.clone(), at(2, 3, 0), at(2, 2, 0), None)
+            .is_err());
+        tracker
+            .add_manual_entry("ben", TrackedDocument::new("detail"), at(3, 10, 0), at(3, 10, 30), None)
+            .unwrap();
+
+        let adjusted = tracker
+            .adjust_entry(
+                entry.id,
+                TimeAdjustment {
+                    end: Some(at(2, 0, 30)),
+                    billable: Some(false),
+                    ..Default::default()
+                },
+                "ana",
+            )
+            .unwrap();
+        assert_eq!(adjusted.original, Some((at(1, 23, 0), at(2, 1, 0))));
+        assert!(adjusted.is_adjusted() && !adjusted.billable);
+
+        let summary = tracker.weekly_summary(at(4, 12, 0), None);
+        assert_eq!(summary.week_start, at(1, 0, 0));
+        assert_eq!(summary.rows.len(), 2);
+        let row = &summary.rows[0];
+        assert_eq!(row.user_id, "ana");
+        assert_eq!(&row.daily_secs[..3], &[3600, 1800, 0]);
+        assert_eq!((row.total_secs, row.billable_secs), (5400, 0));
+        assert_eq!(summary.total_secs, 7200);
+        assert_eq!(summary.project_totals()[&None], 1800);
+        assert_eq!(tracker.weekly_summary(at(4, 12, 0), Some("ben")).rows.len(), 1);
+
+        let csv = summary.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[0].starts_with("user_id,project_id,document_id,document_name,2026-10-12,"));
+        assert_eq!(
+            lines[1],
+            "ana,tower,plan,\"Level 1, plan\",1.00,0.50,0.00,0.00,0.00,0.00,0.00,1.50,0.00"
+        );
+        assert!(entries_csv(&tracker.entries(&TimeQuery::default()))
+            .lines()
+            .nth(1)
+            .unwrap()
+            .ends_with(",1.50,manual,false,true,site visit"));
+
+        tracker.remove_entry(entry.id).unwrap();
+        assert!(tracker.remove_entry(entry.id).is_err());
+        tracker.close_week(at(4, 12, 0));
+        assert_eq!(recorder.0.lock().unwrap().last(), Some(&(EntryChange::Removed, 90)));
+        assert_eq!(*recorder.1.lock().unwrap(), 1);
+    }
+}
@@ -0,0 +1,874 @@
+//! # Admin Console
+//!
+//! Operational view of a self-hosted node for its administrators:
+//!
+//! - **Node Status**: Health of each registered component (cluster, database,
+//!   cache, scheduler), probed concurrently with a timeout so one hung
+//!   dependency cannot stall the status page
+//! - **Configuration Validation**: Checks a deployment configuration for
+//!   errors and risky settings before, or after, it is rolled out
+//! - **Job Backlogs**: Pending, running, retrying and failed background jobs
+//!   per job type, with thresholds that mark the scheduler degraded
+//! - **Maintenance Mode**: Holds write traffic with `503 Service Unavailable`
+//!   while reads and admin endpoints keep working
+//!
+//! The console is exposed under `/api/v1/admin` and every endpoint requires
+//! the `admin` role.
+//!
+//! # Examples
+//!
+//! ```rust,ignore
+//! use caddy::api::admin::*;
+//!
+//! let console = AdminConsole::new(DeploymentConfig::from_env());
+//! console.register_probe("database", Arc::new(pool.clone()));
+//! console.register_backlog_source(scheduler.clone());
+//!
+//! let status = console.node_status().await;
+//! println!("{:?}: {} components", status.status, status.components.len());
+//!
+//! console.enter_maintenance("Upgrading to v0.4", "ops", None);
+//! ```
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::responses::{ComponentHealth, HealthStatus};
+use crate::database::{CacheManager, ConnectionPool};
+use crate::enterprise::cluster::quorum::QuorumManager;
+use crate::scheduling::{Job, JobScheduler, JobStatus};
+
+/// How long a component may take to answer before it is reported unhealthy
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Seconds clients are told to wait when a maintenance window has no end
+pub const DEFAULT_MAINTENANCE_RETRY_AFTER: u64 = 300;
+
+// ============================================================================
+// Health Probes
+// ============================================================================
+
+/// A component whose health is reported on the node status page
+#[async_trait]
+pub trait HealthProbe: Send + Sync {
+    /// Current health of the component
+    async fn probe(&self) -> ComponentHealth;
+}
+
+#[async_trait]
+impl HealthProbe for ConnectionPool {
+    async fn probe(&self) -> ComponentHealth {
+        match self.health_check().await {
+            Ok(result) if result.is_healthy => {
+                let health = if result.idle_connections == 0 {
+                    ComponentHealth::degraded(format!(
+                        "All {} connections are in use",
+                        result.pool_size
+                    ))
+                } else {
+                    ComponentHealth::healthy()
+                };
+                health.with_response_time(result.latency.as_millis() as u64)
+            }
+            Ok(result) => ComponentHealth::unhealthy(
+                result
+                    .error
+                    .unwrap_or_else(|| "Database did not answer".to_string()),
+            )
+            .with_response_time(result.latency.as_millis() as u64),
+            Err(e) => ComponentHealth::unhealthy(e.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl HealthProbe for CacheManager {
+    async fn probe(&self) -> ComponentHealth {
+        let stats = self.stats();
+        let lookups = stats.total_hits + stats.total_misses;
+        // A cold cache misses a lot; only judge it once it has seen traffic
+        if lookups >= 1000 && stats.hit_rate < 0.5 {
+            ComponentHealth::degraded(format!("Hit rate is {:.0}%", stats.hit_rate * 100.0))
+        } else {
+            ComponentHealth::healthy()
+        }
+    }
+}
+
+#[async_trait]
+impl HealthProbe for QuorumManager {
+    async fn probe(&self) -> ComponentHealth {
+        let stats = self.stats().await;
+        let reachable = stats.total_voters.saturating_sub(stats.fenced_nodes);
+        if reachable < stats.quorum_size {
+            ComponentHealth::unhealthy(format!(
+                "Only {} of {} voters available, quorum needs {}",
+                reachable, stats.total_voters, stats.quorum_size
+            ))
+        } else if stats.detected_partitions > 0 || stats.fenced_nodes > 0 {
+            ComponentHealth::degraded(format!(
+                "{} partition(s) detected, {} node(s) fenced",
+                stats.detected_partitions, stats.fenced_nodes
+            ))
+        } else {
+            ComponentHealth::healthy()
+        }
+    }
+}
+
+// ============================================================================
+// Job Backlogs
+// ============================================================================
+
+/// Background jobs of one type that are waiting, running or stuck
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobBacklog {
+    /// Job type, e.g. `trash_purge`
+    pub job_type: String,
+    /// Pending or scheduled jobs
+    pub pending: usize,
+    /// Jobs currently executing
+    pub running: usize,
+    /// Jobs waiting for another attempt
+    pub retrying: usize,
+    /// Jobs that gave up
+    pub failed: usize,
+    /// How long the most overdue waiting job has been due
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oldest_overdue_seconds: Option<u64>,
+}
+
+/// Something that queues background jobs
+#[async_trait]
+pub trait BacklogSource: Send + Sync {
+    /// Backlog per job type
+    async fn backlogs(&self) -> Vec<JobBacklog>;
+}
+
+#[async_trait]
+impl BacklogSource for JobScheduler {
+    async fn backlogs(&self) -> Vec<JobBacklog> {
+        summarize_jobs(&self.list_jobs().await, Utc::now())
+    }
+}
+
+/// Group jobs into backlogs by job type, sorted by job type
+pub fn summarize_jobs(jobs: &[Job], now: DateTime<Utc>) -> Vec<JobBacklog> {
+    let mut backlogs: BTreeMap<&str, JobBacklog> = BTreeMap::new();
+    for job in jobs {
+        let backlog = backlogs
+            .entry(job.job_type.as_str())
+            .or_insert_with(|| JobBacklog {
+                job_type: job.job_type.clone(),
+                ..JobBacklog::default()
+            });
+        let waiting = match job.status {
+            JobStatus::Pending | JobStatus::Scheduled => {
+                backlog.pending += 1;
+                true
+            }
+            JobStatus::Retrying => {
+                backlog.retrying += 1;
+                true
+            }
+            JobStatus::Running => {
+                backlog.running += 1;
+                false
+            }
+            JobStatus::Failed => {
+                backlog.failed += 1;
+                false
+            }
+            JobStatus::Completed | JobStatus::Cancelled => false,
+        };
+        if let Some(due) = job.next_run.filter(|due| waiting && *due < now) {
+            let overdue = (now - due).num_seconds().max(0) as u64;
+            backlog.oldest_overdue_seconds = Some(
+                backlog
+                    .oldest_overdue_seconds
+                    .map_or(overdue, |o| o.max(overdue)),
+            );
+        }
+    }
+    backlogs
+        .into_values()
+        .filter(|b| b.pending + b.running + b.retrying + b.failed > 0)
+        .collect()
+}
+
+/// Backlog sizes past which the scheduler is reported degraded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklogThresholds {
+    /// Waiting jobs of one type
+    pub max_pending: usize,
+    /// Failed jobs of one type
+    pub max_failed: usize,
+    /// Seconds a waiting job may be overdue
+    pub max_overdue_seconds: u64,
+}
+
+impl Default for BacklogThresholds {
+    fn default() -> Self {
+        Self {
+            max_pending: 1000,
+            max_failed: 50,
+            max_overdue_seconds: 900,
+        }
+    }
+}
+
+impl BacklogThresholds {
+    /// Scheduler health implied by the backlogs
+    pub fn assess(&self, backlogs: &[JobBacklog]) -> ComponentHealth {
+        let problems: Vec<String> = backlogs
+            .iter()
+            .flat_map(|b| {
+                let mut problems = Vec::new();
+                if b.pending + b.retrying > self.max_pending {
+                    problems.push(format!(
+                        "{}: {} jobs waiting",
+                        b.job_type,
+                        b.pending + b.retrying
+                    ));
+                }
+                if b.failed > self.max_failed {
+                    problems.push(format!("{}: {} jobs failed", b.job_type, b.failed));
+                }
+                if let Some(overdue) = b
+                    .oldest_overdue_seconds
+                    .filter(|o| *o > self.max_overdue_seconds)
+                {
+                    problems.push(format!("{}: {}s overdue", b.job_type, overdue));
+                }
+                problems
+            })
+            .collect();
+        if problems.is_empty() {
+            ComponentHealth::healthy()
+        } else {
+            ComponentHealth::degraded(problems.join("; "))
+        }
+    }
+}
+
+// ============================================================================
+// Deployment Configuration
+// ============================================================================
+
+/// Settings of a self-hosted node
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentConfig {
+    /// URL users reach the node at
+    pub public_url: String,
+    /// Database connection URL
+    pub database_url: String,
+    /// Redis URL for the shared cache and sessions
+    #[serde(default)]
+    pub redis_url: Option<String>,
+    /// Secret used to sign session tokens
+    #[serde(default, skip_serializing)]
+    pub jwt_secret: String,
+    /// Directory for uploaded files and exports
+    pub storage_path: String,
+    /// This node's ID in the cluster
+    #[serde(default)]
+    pub node_id: String,
+    /// Addresses of the other cluster nodes
+    #[serde(default)]
+    pub cluster_peers: Vec<String>,
+    /// Maximum database connections
+    pub max_connections: u32,
+    /// Request timeout in seconds
+    pub request_timeout_secs: u64,
+}
+
+impl DeploymentConfig {
+    /// Read the configuration from `CADDY_*` environment variables
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        Self {
+            public_url: var("CADDY_PUBLIC_URL"),
+            database_url: var("CADDY_DATABASE_URL"),
+            redis_url: std::env::var("CADDY_REDIS_URL")
+                .ok()
+                .filter(|url| !url.is_empty()),
+            jwt_secret: var("CADDY_JWT_SECRET"),
+            storage_path: var("CADDY_STORAGE_PATH"),
+            node_id: var("CADDY_NODE_ID"),
+            cluster_peers: var("CADDY_CLUSTER_PEERS")
+                .split(',')
+                .map(str::trim)
+                .filter(|peer| !peer.is_empty())
+                .map(String::from)
+                .collect(),
+            max_connections: var("CADDY_MAX_CONNECTIONS").parse().unwrap_or(100),
+            request_timeout_secs: var("CADDY_REQUEST_TIMEOUT_SECS").parse().unwrap_or(30),
+        }
+    }
+
+    /// Check the configuration for errors and risky settings
+    pub fn validate(&self) -> ConfigReport {
+        let mut report = ConfigReport::default();
+
+        match self.public_url.split_once("://") {
+            Some(("https", host)) if !host.is_empty() => {}
+            Some(("http", host)) if !host.is_empty() => report.warning(
+                "publicUrl",
+                "Served over plain HTTP; session tokens travel unencrypted",
+            ),
+            _ => report.error("publicUrl", "Must be an http:// or https:// URL"),
+        }
+
+        match self.database_url.split_once("://") {
+            Some(("postgres" | "postgresql" | "mysql" | "sqlite", rest)) if !rest.is_empty() => {}
+            Some((scheme, _)) if !scheme.is_empty() => {
+                report.error("databaseUrl", format!("Unsupported database '{}'", scheme))
+            }
+            _ => report.error("databaseUrl", "Missing database connection URL"),
+        }
+
+        if self.jwt_secret.is_empty() {
+            report.error("jwtSecret", "Missing token signing secret");
+        } else if self.jwt_secret.len() < 32 {
+            report.error("jwtSecret", "Must be at least 32 characters");
+        } else if self.jwt_secret.chars().collect::<HashSet<_>>().len() < 8 {
+            report.warning(
+                "jwtSecret",
+                "Has very few distinct characters; use a random secret",
+            );
+        }
+
+        if self.storage_path.is_empty() {
+            report.error("storagePath", "Missing storage directory");
+        } else if !std::path::Path::new(&self.storage_path).is_dir() {
+            report.warning("storagePath", "Directory does not exist on this node");
+        }
+
+        if self.max_connections == 0 {
+            report.error("maxConnections", "Must be at least 1");
+        }
+        if self.request_timeout_secs == 0 {
+            report.error("requestTimeoutSecs", "Must be at least 1");
+        }
+
+        if !self.cluster_peers.is_empty() {
+            if self.node_id.is_empty() {
+                report.error("nodeId", "Required when cluster peers are configured");
+            }
+            let mut seen = HashSet::new();
+            for peer in &self.cluster_peers {
+                if !seen.insert(peer) {
+                    report.error("clusterPeers", format!("Peer '{}' is listed twice", peer));
+                }
+            }
+            // Peers plus this node
+            if (seen.len() + 1) % 2 == 0 {
+                report.warning(
+                    "clusterPeers",
+                    format!(
+                        "{} voting nodes can split evenly; use an odd number",
+                        seen.len() + 1
+                    ),
+                );
+            }
+            if self.redis_url.is_none() {
+                report.warning(
+                    "redisUrl",
+                    "Clustered nodes without Redis do not share caches or sessions",
+                );
+            }
+        }
+
+        report
+    }
+}
+
+/// How serious a configuration problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigSeverity {
+    /// The node will not work correctly
+    Error,
+    /// The node works, but the setting is risky
+    Warning,
+}
+
+/// One configuration problem
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigIssue {
+    /// Setting the problem is with
+    pub key: String,
+    pub severity: ConfigSeverity,
+    pub message: String,
+}
+
+/// Result of validating a deployment configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigReport {
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ConfigReport {
+    /// Whether there are no errors; warnings are allowed
+    pub fn is_valid(&self) -> bool {
+        self.issues
+            .iter()
+            .all(|i| i.severity != ConfigSeverity::Error)
+    }
+
+    fn error(&mut self, key: &str, message: impl Into<String>) {
+        self.push(key, ConfigSeverity::Error, message);
+    }
+
+    fn warning(&mut self, key: &str, message: impl Into<String>) {
+        self.push(key, ConfigSeverity::Warning, message);
+    }
+
+    fn push(&mut self, key: &str, severity: ConfigSeverity, message: impl Into<String>) {
+        self.issues.push(ConfigIssue {
+            key: key.to_string(),
+            severity,
+            message: message.into(),
+        });
+    }
+}
+
+// ============================================================================
+// Maintenance Mode
+// ============================================================================
+
+/// An active maintenance window
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceWindow {
+    /// Shown to clients whose writes are held
+    pub message: String,
+    pub started_at: DateTime<Utc>,
+    /// Admin who started the window
+    pub started_by: String,
+    /// Expected end, used for `Retry-After`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+impl MaintenanceWindow {
+    /// Seconds clients should wait before retrying
+    pub fn retry_after(&self, now: DateTime<Utc>) -> u64 {
+        self.ends_at.map_or(DEFAULT_MAINTENANCE_RETRY_AFTER, |end| {
+            (end - now).num_seconds().max(1) as u64
+        })
+    }
+}
+
+// ============================================================================
+// Admin Console
+// ============================================================================
+
+/// Health of this node and its components
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStatus {
+    pub node_id: String,
+    pub version: String,
+    pub started_at: DateTime<Utc>,
+    pub uptime_seconds: u64,
+    /// Worst component status
+    pub status: HealthStatus,
+    pub components: BTreeMap<String, ComponentHealth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maintenance: Option<MaintenanceWindow>,
+}
+
+/// Admin view of a self-hosted node
+pub struct AdminConsole {
+    config: RwLock<DeploymentConfig>,
+    started_at: DateTime<Utc>,
+    started: Instant,
+    probes: RwLock<Vec<(String, Arc<dyn HealthProbe>)>>,
+    backlog_sources: RwLock<Vec<Arc<dyn BacklogSource>>>,
+    thresholds: RwLock<BacklogThresholds>,
+    probe_timeout: Duration,
+    maintenance: RwLock<Option<MaintenanceWindow>>,
+}
+
+impl AdminConsole {
+    /// Create a console for a node running with `config`
+    pub fn new(config: DeploymentConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            started_at: Utc::now(),
+            started: Instant::now(),
+            probes: RwLock::new(Vec::new()),
+            backlog_sources: RwLock::new(Vec::new()),
+            thresholds: RwLock::new(BacklogThresholds::default()),
+            probe_timeout: DEFAULT_PROBE_TIMEOUT,
+            maintenance: RwLock::new(None),
+        }
+    }
+
+    /// Set how long each probe may take
+    pub fn with_probe_timeout(mut self, timeout: Duration) -> Self {
+        self.probe_timeout = timeout;
+        self
+    }
+
+    /// Report a component on the status page, replacing any probe of the
+    /// same name
+    pub fn register_probe(&self, component: impl Into<String>, probe: Arc<dyn HealthProbe>) {
+        let component = component.into();
+        let mut probes = self.probes.write();
+        probes.retain(|(name, _)| *name != component);
+        probes.push((component, probe));
+    }
+
+    /// Include a job queue in the backlog report and scheduler health
+    pub fn register_backlog_source(&self, source: Arc<dyn BacklogSource>) {
+        self.backlog_sources.write().push(source);
+    }
+
+    /// Change when backlogs mark the scheduler degraded
+    pub fn set_thresholds(&self, thresholds: BacklogThresholds) {
+        *self.thresholds.write() = thresholds;
+    }
+
+    pub fn thresholds(&self) -> BacklogThresholds {
+        *self.thresholds.read()
+    }
+
+    /// Seconds since the node started
+    pub fn uptime_seconds(&self) -> u64 {
+        self.started.elapsed().as_secs()
+    }
+
+    /// Probe every component and summarize the node
+    ///
+    /// Probes run concurrently. One that does not answer within the probe
+    /// timeout is reported unhealthy. When backlog sources are registered
+    /// and no probe is named `scheduler`, the scheduler's health is derived
+    /// from the backlog thresholds.
+    pub async fn node_status(&self) -> NodeStatus {
+        let probes = self.probes.read().clone();
+        let timeout = self.probe_timeout;
+        let checks = probes.into_iter().map(|(name, probe)| async move {
+            let start = Instant::now();
+            let health = match tokio::time::timeout(timeout, probe.probe()).await {
+                Ok(health) => health,
+                Err(_) => ComponentHealth::unhealthy(format!(
+                    "No answer within {}ms",
+                    timeout.as_millis()
+                )),
+            };
+            let health = match health.response_time_ms {
+                Some(_) => health,
+                None => health.with_response_time(start.elapsed().as_millis() as u64),
+            };
+            (name, health)
+        });
+        let mut components: BTreeMap<String, ComponentHealth> = futures::future::join_all(checks)
+            .await
+            .into_iter()
+            .collect();
+
+        if !components.contains_key("scheduler") && !self.backlog_sources.read().is_empty() {
+            let backlogs = self.job_backlogs().await;
+            components.insert("scheduler".to_string(), self.thresholds().assess(&backlogs));
+        }
+
+        let status = components
+            .values()
+            .map(|c| c.status)
+            .max_by_key(|s| match s {
+                HealthStatus::Healthy => 0,
+                HealthStatus::Degraded => 1,
+                HealthStatus::Unhealthy => 2,
+            })
+            .unwrap_or(HealthStatus::Healthy);
+
+        NodeStatus {
+            node_id: self.config.read().node_id.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            started_at: self.started_at,
+            uptime_seconds: self.uptime_seconds(),
+            status,
+            components,
+            maintenance: self.maintenance(),
+        }
+    }
+
+    /// Backlogs of every registered source, merged by job type
+    pub async fn job_backlogs(&self) -> Vec<JobBacklog> {
+        let sources = self.backlog_sources.read().clone();
+        let mut merged: BTreeMap<String, JobBacklog> = BTreeMap::new();
+        for backlogs in futures::future::join_all(sources.iter().map(|s| s.backlogs())).await {
+            for backlog in backlogs {
+                let entry = merged
+                    .entry(backlog.job_type.clone())
+                    .or_insert_with(|| JobBacklog {
+                        job_type: backlog.job_type.clone(),
+                        ..JobBacklog::default()
+                    });
+                entry.pending += backlog.pending;
+                entry.running += backlog.running;
+                entry.retrying += backlog.retrying;
+                entry.failed += backlog.failed;
+                entry.oldest_overdue_seconds = entry
+                    .oldest_overdue_seconds
+                    .max(backlog.oldest_overdue_seconds);
+            }
+        }
+        merged.into_values().collect()
+    }
+
+    /// The configuration the node is running with
+    pub fn config(&self) -> DeploymentConfig {
+        self.config.read().clone()
+    }
+
+    /// Validate the running configuration
+    pub fn validate_config(&self) -> ConfigReport {
+        self.config.read().validate()
+    }
+
+    /// Hold write traffic until [`exit_maintenance`](Self::exit_maintenance)
+    pub fn enter_maintenance(
+        &self,
+        message: impl Into<String>,
+        started_by: impl Into<String>,
+        ends_at: Option<DateTime<Utc>>,
+    ) -> MaintenanceWindow {
+        let window = MaintenanceWindow {
+            message: message.into(),
+            started_at: Utc::now(),
+            started_by: started_by.into(),
+            ends_at,
+        };
+        *self.maintenance.write() = Some(window.clone());
+        window
+    }
+
+    /// End maintenance, returning the window that was active
+    pub fn exit_maintenance(&self) -> Option<MaintenanceWindow> {
+        self.maintenance.write().take()
+    }
+
+    /// The active maintenance window
+    pub fn maintenance(&self) -> Option<MaintenanceWindow> {
+        self.maintenance.read().clone()
+    }
+
+    pub fn in_maintenance(&self) -> bool {
+        self.maintenance.read().is_some()
+    }
+}
+
+impl Default for AdminConsole {
+    fn default() -> Self {
+        Self::new(DeploymentConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduling::JobSchedule;
+
+    struct FixedProbe(ComponentHealth);
+
+    #[async_trait]
+    impl HealthProbe for FixedProbe {
+        async fn probe(&self) -> ComponentHealth {
+            self.0.clone()
+        }
+    }
+
+    struct HungProbe;
+
+    #[async_trait]
+    impl HealthProbe for HungProbe {
+        async fn probe(&self) -> ComponentHealth {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            ComponentHealth::healthy()
+        }
+    }
+
+    struct FixedBacklog(Vec<JobBacklog>);
+
+    #[async_trait]
+    impl BacklogSource for FixedBacklog {
+        async fn backlogs(&self) -> Vec<JobBacklog> {
+            self.0.clone()
+        }
+    }
+
+    fn valid_config() -> DeploymentConfig {
+        DeploymentConfig {
+            public_url: "https://cad.example.com".to_string(),
+            database_url: "postgres://caddy@db/caddy".to_string(),
+            redis_url: Some("redis://cache".to_string()),
+            jwt_secret: "k3Jx9vQ2mP7tR4wZ8yB1nC6fH0sL5dGa".to_string(),
+            storage_path: std::env::temp_dir().to_string_lossy().into_owned(),
+            node_id: "node-1".to_string(),
+            cluster_peers: vec!["node-2:7000".to_string(), "node-3:7000".to_string()],
+            max_connections: 50,
+            request_timeout_secs: 30,
+        }
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let report = valid_config().validate();
+        assert!(
+            report.is_valid() && report.issues.is_empty(),
+            "{:?}",
+            report
+        );
+
+        let config = DeploymentConfig {
+            public_url: "http://cad.local".to_string(),
+            database_url: "oracle://db".to_string(),
+            jwt_secret: "short".to_string(),
+            cluster_peers: vec!["node-2:7000".to_string(), "node-2:7000".to_string()],
+            redis_url: None,
+            max_connections: 0,
+            ..valid_config()
+        };
+        let report = config.validate();
+        assert!(!report.is_valid());
+        let keys = |severity| -> Vec<&str> {
+            report
+                .issues
+                .iter()
+                .filter(|i| i.severity == severity)
+                .map(|i| i.key.as_str())
+                .collect()
+        };
+        assert_eq!(
+            keys(ConfigSeverity::Error),
+            ["databaseUrl", "jwtSecret", "maxConnections", "clusterPeers"]
+        );
+        assert_eq!(
+            keys(ConfigSeverity::Warning),
+            ["publicUrl", "clusterPeers", "redisUrl"]
+        );
+
+        // The signing secret never leaves the node
+        let json = serde_json::to_value(valid_config()).unwrap();
+        assert!(json.get("jwtSecret").is_none());
+    }
+
+    #[test]
+    fn test_summarize_jobs() {
+        let now = Utc::now();
+        let job = |job_type: &str, status: JobStatus, due_secs_ago: i64| {
+            let due = now - chrono::Duration::seconds(due_secs_ago);
+            let mut job = Job::new(
+                "job".to_string(),
+                job_type.to_string(),
+                JobSchedule::Once(due),
+            );
+            job.status = status;
+            job.next_run = Some(due);
+            job
+        };
+        let jobs = [
+            job("export", JobStatus::Pending, 120),
+            job("export", JobStatus::Retrying, 600),
+            job("export", JobStatus::Running, 900),
+            job("export", JobStatus::Completed, 5000),
+            job("purge", JobStatus::Failed, 10),
+            job("purge", JobStatus::Scheduled, -60),
+            job("thumbnails", JobStatus::Completed, 0),
+        ];
+
+        let backlogs = summarize_jobs(&jobs, now);
+        assert_eq!(backlogs.len(), 2);
+        assert_eq!(
+            backlogs[0],
+            JobBacklog {
+                job_type: "export".to_string(),
+                pending: 1,
+                running: 1,
+                retrying: 1,
+                failed: 0,
+                oldest_overdue_seconds: Some(600),
+            }
+        );
+        assert_eq!(
+            (
+                backlogs[1].pending,
+                backlogs[1].failed,
+                backlogs[1].oldest_overdue_seconds
+            ),
+            (1, 1, None)
+        );
+
+        let thresholds = BacklogThresholds {
+            max_overdue_seconds: 300,
+            ..BacklogThresholds::default()
+        };
+        let health = thresholds.assess(&backlogs);
+        assert_eq!(health.status, HealthStatus::Degraded);
+        assert_eq!(health.message.as_deref(), Some("export: 600s overdue"));
+    }
+
+    #[tokio::test]
+    async fn test_node_status() {
+        let console =
+            AdminConsole::new(valid_config()).with_probe_timeout(Duration::from_millis(50));
+        console.register_probe("database", Arc::new(FixedProbe(ComponentHealth::healthy())));
+        console.register_probe(
+            "cache",
+            Arc::new(FixedProbe(ComponentHealth::degraded("Hit rate is 20%"))),
+        );
+        console.register_backlog_source(Arc::new(FixedBacklog(vec![JobBacklog {
+            job_type: "export".to_string(),
+            pending: 3,
+            ..JobBacklog::default()
+        }])));
+
+        let status = console.node_status().await;
+        assert_eq!(status.node_id, "node-1");
+        assert_eq!(status.status, HealthStatus::Degraded);
+        assert_eq!(status.components["scheduler"].status, HealthStatus::Healthy);
+        assert!(status.components["database"].response_time_ms.is_some());
+
+        // A hung dependency is reported, not waited on
+        console.register_probe("cluster", Arc::new(HungProbe));
+        let status = console.node_status().await;
+        assert_eq!(status.status, HealthStatus::Unhealthy);
+        assert_eq!(
+            status.components["cluster"].message.as_deref(),
+            Some("No answer within 50ms")
+        );
+        assert_eq!(status.components.len(), 4);
+    }
+
+    #[test]
+    fn test_maintenance_window() {
+        let console = AdminConsole::default();
+        assert!(!console.in_maintenance());
+
+        let ends_at = Utc::now() + chrono::Duration::seconds(120);
+        let window = console.enter_maintenance("Upgrading", "ops", Some(ends_at));
+        assert!(console.in_maintenance());
+        assert!((119..=120).contains(&window.retry_after(Utc::now())));
+
+        assert_eq!(console.exit_maintenance(), Some(window));
+        assert!(console.maintenance().is_none());
+        let open_ended = console.enter_maintenance("Upgrading", "ops", None);
+        assert_eq!(
+            open_ended.retry_after(Utc::now()),
+            DEFAULT_MAINTENANCE_RETRY_AFTER
+        );
+    }
+}
@@ -5,7 +5,8 @@
 //! - **Request Routing**: Route requests to appropriate backend services
 //! - **API Versioning**: Support for multiple API versions (v1, v2, etc.)
 //! - **Circuit Breaker**: Automatic failure detection and recovery
-//! - **Retry Logic**: Exponential backoff retry for transient failures
+//! - **Retry Logic**: Exponential backoff retry for transient failures, on the
+//!   shared schedule from `enterprise::resilience`
 //! - **Load Balancing**: Distribute requests across multiple backend instances
 //! - **Request/Response Transformation**: Modify requests and responses
 //! - **Service Discovery**: Dynamic backend service discovery
@@ -37,6 +38,7 @@ use async_trait::async_trait;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use tokio::time::sleep;
+use crate::enterprise::resilience::BackoffPolicy;
 
 // ============================================================================
 // Gateway Configuration
@@ -139,6 +141,14 @@ impl Default for RetryConfig {
     }
 }
 
+impl From<&RetryConfig> for BackoffPolicy {
+    fn from(config: &RetryConfig) -> Self {
+        BackoffPolicy::exponential(config.initial_delay, config.max_delay, config.backoff_multiplier)
+            .with_jitter(config.jitter)
+            .with_max_attempts(Some(config.max_attempts))
+    }
+}
+
 /// Backend service configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackendConfig {
@@ -371,8 +381,8 @@ impl RetryPolicy {
         Fut: std::future::Future<Output = Result<T, E>>,
         E: std::fmt::Debug,
     {
+        let backoff = BackoffPolicy::from(&self.config);
         let mut attempt = 0;
-        let mut delay = self.config.initial_delay;
 
         loop {
             attempt += 1;
@@ -380,7 +390,7 @@ impl RetryPolicy {
             match operation().await {
                 Ok(result) => return Ok(result),
                 Err(err) => {
-                    if attempt >= self.config.max_attempts {
+                    if !backoff.should_retry(attempt) {
                         tracing::error!(
                             "Request failed after {} attempts: {:?}",
                             attempt,
@@ -389,15 +399,7 @@ impl RetryPolicy {
                         return Err(err);
                     }
 
-                    // Calculate next delay with exponential backoff and jitter
-                    let jitter_amount = delay.as_millis() as f64 * self.config.jitter;
-                    let jitter = rand::random::<f64>() * jitter_amount * 2.0 - jitter_amount;
-                    let next_delay = delay.mul_f64(self.config.backoff_multiplier);
-                    let next_delay = Duration::from_millis(
-                        (next_delay.as_millis() as f64 + jitter) as u64,
-                    );
-
-                    delay = next_delay.min(self.config.max_delay);
+                    let delay = backoff.delay(attempt - 1);
 
                     tracing::warn!(
                         "Request failed (attempt {}/{}), retrying after {:?}",
@@ -9,6 +9,15 @@
 //! - Bulk operations for batch processing
 //! - Site/project management
 //! - Configuration and settings
+//! - Recycle bin listing and restore
+//! - Document access history
+//! - Feature flag rollouts (admin)
+//! - Node status, configuration checks, job backlogs and maintenance mode (admin)
+//! - License seat usage reports and idle-seat reclamation (admin)
+//! - Project documentation reports, on demand or scheduled
+//! - Time tracking entries and weekly timesheets
+//! - Document and block thumbnails
+//! - Entity changes in open drawings
 //!
 //! # Examples
 //!
@@ -24,18 +33,43 @@
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::IntoResponse,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use super::admin::{
+    AdminConsole, BacklogThresholds, ConfigIssue, DeploymentConfig, JobBacklog, MaintenanceWindow,
+};
 use super::middleware::UserContext;
 use super::responses::*;
+use super::webhooks::WebhookManager;
+use crate::commands::DocumentSet;
+use crate::analytics::timetracking::{
+    entries_csv, TimeAdjustment, TimeQuery, TimeTracker, TimeTrackingError, TrackedDocument,
+};
+use crate::enterprise::collaboration::sharing::{
+    share_url, ShareAccess, ShareError, ShareLink, ShareLinkManager, ShareRequest,
+};
+use crate::enterprise::compliance::access::{
+    AccessHistory, AccessQuery, DocumentAccess, DocumentAction,
+};
+use crate::enterprise::licensing::seats::{ReportPeriod, SeatError, SeatManager};
+use crate::enterprise::tenant::config::TenantConfig;
+use crate::enterprise::tenant::context::TenantId;
+use crate::enterprise::tenant::rollout::{
+    Cohort, FlagChange, FlagRollout, FlagUpdate, RolloutError, RolloutManager,
+};
+use crate::io::trash::{RecycleBin, TrashItem, TrashedObject};
+use crate::rendering::{PreviewService, PreviewSize, PreviewSubject};
+use crate::scheduling::{JobSchedule, SchedulerError};
+use crate::sheets::{ProjectReports, SheetError};
 
 // ============================================================================
 // Shared State
@@ -49,6 +83,40 @@ pub struct AppState {
 
     /// Configuration
     pub config: Arc<AppConfig>,
+
+    /// Soft-deleted entities and documents
+    pub trash: Arc<RwLock<RecycleBin>>,
+
+    /// Webhook registrations and delivery history
+    pub webhooks: WebhookManager,
+
+    /// Who viewed, edited, exported or shared each document
+    pub access_history: AccessHistory,
+
+    /// Review links to document revisions, logging to `access_history`
+    pub share_links: ShareLinkManager,
+
+    /// Progressive feature flag rollouts, shared with the tenant config manager
+    pub flag_rollouts: Arc<RolloutManager>,
+
+    /// Node health, configuration checks, job backlogs and maintenance mode
+    pub admin: Arc<AdminConsole>,
+
+    /// License seats and their reclamation workflow, when seat tracking is
+    /// configured; shared with the seat jobs
+    pub seats: Option<Arc<RwLock<SeatManager>>>,
+
+    /// Project documentation reports and their schedules, when configured
+    pub reports: Option<Arc<ProjectReports>>,
+
+    /// Time spent per drawing and project, when time tracking is configured
+    pub time_tracking: Option<Arc<TimeTracker>>,
+
+    /// Cached document and block previews, when thumbnails are configured
+    pub previews: Option<Arc<PreviewService>>,
+
+    /// The editor's open drawings, when it shares them for change queries
+    pub drawings: Option<Arc<tokio::sync::Mutex<DocumentSet>>>,
 }
 
 /// Application configuration
@@ -654,6 +722,1252 @@ pub async fn update_settings(
     Ok(ApiResponse::success(settings, "Settings updated successfully"))
 }
 
+// ============================================================================
+// Recycle Bin Handlers
+// ============================================================================
+
+/// Recycle bin item without its content
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashItemSummary {
+    pub id: Uuid,
+    pub kind: String,
+    pub object_id: Uuid,
+    pub document_id: Option<Uuid>,
+    pub label: String,
+    pub deleted_at: DateTime<Utc>,
+    pub deleted_by: String,
+    pub reason: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl From<&TrashItem> for TrashItemSummary {
+    fn from(item: &TrashItem) -> Self {
+        Self {
+            id: item.id,
+            kind: item.kind().to_string(),
+            object_id: item.object_id(),
+            document_id: match &item.object {
+                TrashedObject::Entity { document_id, .. } => Some(*document_id),
+                TrashedObject::Document(_) => None,
+            },
+            label: item.label(),
+            deleted_at: item.deleted_at,
+            deleted_by: item.deleted_by.clone(),
+            reason: item.reason.clone(),
+            expires_at: item.expires_at,
+        }
+    }
+}
+
+/// Query parameters for listing the recycle bin
+#[derive(Debug, Deserialize)]
+pub struct ListTrashQuery {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+    /// `entity` or `document`
+    pub kind: Option<String>,
+    pub document_id: Option<Uuid>,
+}
+
+/// List recycle bin items, oldest deletion first
+pub async fn list_trash(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ListTrashQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params
+        .per_page
+        .unwrap_or(state.config.default_page_size)
+        .min(state.config.max_page_size);
+
+    let bin = state.trash.read().await;
+    let matching: Vec<TrashItemSummary> = bin
+        .items()
+        .iter()
+        .map(TrashItemSummary::from)
+        .filter(|item| params.kind.as_deref().is_none_or(|kind| item.kind == kind))
+        .filter(|item| params.document_id.is_none_or(|id| item.document_id == Some(id)))
+        .collect();
+    let total = matching.len() as u64;
+    let items: Vec<TrashItemSummary> = matching
+        .into_iter()
+        .skip(((page - 1) * per_page) as usize)
+        .take(per_page as usize)
+        .collect();
+
+    let pagination = PaginationMeta::offset(page, per_page, total);
+    let links = PaginationLinks::new(
+        &format!("{}/api/v1/trash", state.config.base_url),
+        page,
+        pagination.total_pages.unwrap_or(1),
+    );
+
+    Ok(PaginatedResponse::new(items, total, pagination).with_links(links))
+}
+
+/// Get a recycle bin item
+pub async fn get_trash_item(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let bin = state.trash.read().await;
+    let item = bin.get(item_id).ok_or_else(|| trash_item_not_found(item_id))?;
+
+    Ok(ApiResponse::success(
+        TrashItemSummary::from(item),
+        "Recycle bin item retrieved successfully",
+    ))
+}
+
+/// Restore a recycle bin item
+///
+/// The item leaves the bin and is returned in full (entity or document
+/// content included) so it can be written back to document storage.
+pub async fn restore_trash_item(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let item = state
+        .trash
+        .write()
+        .await
+        .take(item_id)
+        .map_err(|_| trash_item_not_found(item_id))?;
+
+    // TODO: Write the restored entity/document back to the database
+    Ok(ApiResponse::success(item, "Item restored successfully"))
+}
+
+fn trash_item_not_found(item_id: Uuid) -> ApiError {
+    ApiError::not_found(format!("trash/{}", item_id), "Recycle bin item not found")
+}
+
+// ============================================================================
+// Document Access History Handlers
+// ============================================================================
+
+/// Query parameters for a document's access history
+#[derive(Debug, Deserialize)]
+pub struct AccessHistoryQuery {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+    pub user: Option<String>,
+    /// Comma-separated actions: `view`, `edit`, `export`, `share`
+    pub actions: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// Access to report for a document
+#[derive(Debug, Deserialize)]
+pub struct RecordAccessRequest {
+    pub action: DocumentAction,
+    #[serde(default)]
+    pub details: HashMap<String, String>,
+}
+
+/// List a document's accesses, newest first
+pub async fn list_document_access(
+    State(state): State<Arc<AppState>>,
+    Path(document_id): Path<Uuid>,
+    Query(params): Query<AccessHistoryQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params
+        .per_page
+        .unwrap_or(state.config.default_page_size)
+        .min(state.config.max_page_size);
+
+    let mut actions = Vec::new();
+    for name in params.actions.as_deref().unwrap_or_default().split(',').filter(|n| !n.is_empty()) {
+        let action = DocumentAction::from_name(name.trim()).ok_or_else(|| {
+            ApiError::validation_error(vec![FieldError::new(
+                "actions",
+                "INVALID_ACTION",
+                format!("Unknown action '{}'", name),
+            )])
+        })?;
+        actions.push(action);
+    }
+    let query = AccessQuery {
+        user: params.user,
+        actions,
+        since: params.since,
+        until: params.until,
+    };
+
+    let matching = state.access_history.history(document_id, &query).await;
+    let total = matching.len() as u64;
+    let items: Vec<DocumentAccess> = matching
+        .into_iter()
+        .skip(((page - 1) * per_page) as usize)
+        .take(per_page as usize)
+        .collect();
+
+    let pagination = PaginationMeta::offset(page, per_page, total);
+    let links = PaginationLinks::new(
+        &format!("{}/api/v1/documents/{}/access", state.config.base_url, document_id),
+        page,
+        pagination.total_pages.unwrap_or(1),
+    );
+
+    Ok(PaginatedResponse::new(items, total, pagination).with_links(links))
+}
+
+/// Counts, users and latest view and edit for a document
+pub async fn get_document_access_summary(
+    State(state): State<Arc<AppState>>,
+    Path(document_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let summary = state.access_history.summary(document_id).await;
+    Ok(ApiResponse::success(summary, "Access summary retrieved successfully"))
+}
+
+/// Record an access to a document by the calling user
+pub async fn record_document_access(
+    State(state): State<Arc<AppState>>,
+    Path(document_id): Path<Uuid>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+    Json(request): Json<RecordAccessRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = user_ctx
+        .map(|axum::Extension(ctx)| ctx.user_id)
+        .ok_or_else(|| ApiError::bad_request("Access can only be recorded for an authenticated user"))?;
+
+    let mut access = DocumentAccess::new(document_id, user, request.action);
+    access.details = request.details;
+    state.access_history.record(access.clone()).await;
+
+    Ok((
+        StatusCode::CREATED,
+        ApiResponse::success(access, "Access recorded successfully"),
+    ))
+}
+
+// ============================================================================
+// Share Link Handlers
+// ============================================================================
+
+/// Review link to create for a document
+#[derive(Debug, Deserialize)]
+pub struct CreateShareLinkRequest {
+    pub revision: Uuid,
+    #[serde(default)]
+    pub access: ShareAccess,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub label: Option<String>,
+}
+
+/// A new share link with the URL to send; the token in it is not shown
+/// again
+#[derive(Debug, Serialize)]
+pub struct CreatedShareLink {
+    #[serde(flatten)]
+    pub link: ShareLink,
+    pub url: String,
+}
+
+/// List a document's share links, newest first
+pub async fn list_share_links(
+    State(state): State<Arc<AppState>>,
+    Path(document_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let links = state.share_links.links_for(document_id).await;
+    Ok(ApiResponse::success(links, "Share links retrieved successfully"))
+}
+
+/// Create a review link to a document revision for the calling user
+pub async fn create_share_link(
+    State(state): State<Arc<AppState>>,
+    Path(document_id): Path<Uuid>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+    Json(request): Json<CreateShareLinkRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = require_user(user_ctx)?;
+    let request = ShareRequest {
+        document_id,
+        revision: request.revision,
+        access: request.access,
+        expires_at: request.expires_at,
+        label: request.label,
+    };
+    let (link, token) = state
+        .share_links
+        .create(request, &user.user_id)
+        .await
+        .map_err(share_error)?;
+    let url = share_url(&state.config.base_url, &token);
+
+    Ok((
+        StatusCode::CREATED,
+        ApiResponse::success(CreatedShareLink { link, url }, "Share link created successfully"),
+    ))
+}
+
+/// Revoke a share link; only its creator or an administrator may
+pub async fn revoke_share_link(
+    State(state): State<Arc<AppState>>,
+    Path((document_id, link_id)): Path<(Uuid, Uuid)>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = require_user(user_ctx)?;
+    let link = state
+        .share_links
+        .get(link_id)
+        .await
+        .filter(|link| link.document_id == document_id)
+        .ok_or_else(|| share_error(ShareError::NotFound))?;
+    if link.created_by != user.user_id && !user.has_role("admin") {
+        return Err(ApiError::forbidden("Only administrators can revoke other users' share links"));
+    }
+    let link = state.share_links.revoke(link_id, &user.user_id).await.map_err(share_error)?;
+    Ok(ApiResponse::success(link, "Share link revoked successfully"))
+}
+
+/// Open a share link: the document revision and permission the web viewer
+/// session is granted. Public; the token is the credential.
+pub async fn open_share_link(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut details = HashMap::new();
+    for (name, detail) in [("user-agent", "user_agent"), ("x-forwarded-for", "client")] {
+        if let Some(value) = headers.get(name).and_then(|v| v.to_str().ok()) {
+            details.insert(detail.to_string(), value.to_string());
+        }
+    }
+    let grant = state.share_links.open(&token, details).await.map_err(share_error)?;
+    Ok(ApiResponse::success(grant, "Share link opened successfully"))
+}
+
+// ============================================================================
+// Thumbnail Handlers
+// ============================================================================
+
+/// Size of the thumbnail to serve
+#[derive(Debug, Default, Deserialize)]
+pub struct ThumbnailQuery {
+    #[serde(default)]
+    pub size: PreviewSize,
+}
+
+/// PNG preview of a document
+pub async fn get_document_thumbnail(
+    State(state): State<Arc<AppState>>,
+    Path(document_id): Path<Uuid>,
+    Query(params): Query<ThumbnailQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    serve_thumbnail(&state, document_id, PreviewSubject::Document, params.size, &headers).await
+}
+
+/// PNG preview of one of a document's block definitions
+pub async fn get_block_thumbnail(
+    State(state): State<Arc<AppState>>,
+    Path((document_id, block)): Path<(Uuid, String)>,
+    Query(params): Query<ThumbnailQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    serve_thumbnail(&state, document_id, PreviewSubject::Block(block), params.size, &headers).await
+}
+
+/// Serve a cached preview, or render it off the async runtime; a client
+/// already holding the current one gets 304 Not Modified
+async fn serve_thumbnail(
+    state: &AppState,
+    document_id: Uuid,
+    subject: PreviewSubject,
+    size: PreviewSize,
+    headers: &HeaderMap,
+) -> Result<Response, ApiError> {
+    let previews = state
+        .previews
+        .clone()
+        .ok_or_else(|| ApiError::service_unavailable("Thumbnails are not configured"))?;
+    let missing = match &subject {
+        PreviewSubject::Document => format!("Document {} has not been published", document_id),
+        PreviewSubject::Block(name) => format!("Document {} has no block '{}'", document_id, name),
+    };
+    let preview = tokio::task::spawn_blocking(move || previews.preview(document_id, &subject, size))
+        .await
+        .map_err(|e| ApiError::internal_error(format!("Thumbnail rendering failed: {}", e)))?
+        .map_err(|e| ApiError::internal_error(e.to_string()))?
+        .ok_or_else(|| ApiError::not_found("thumbnail", missing))?;
+
+    let current = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|tags| tags.split(',').any(|tag| tag.trim() == preview.etag));
+    if current {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, preview.etag)]).into_response());
+    }
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/png".to_string()),
+            (header::CACHE_CONTROL, "no-cache".to_string()),
+            (header::ETAG, preview.etag),
+        ],
+        preview.png.to_vec(),
+    )
+        .into_response())
+}
+
+// ============================================================================
+// Entity Change Handlers
+// ============================================================================
+
+/// Document version the client has already seen
+#[derive(Debug, Default, Deserialize)]
+pub struct ChangesQuery {
+    #[serde(default)]
+    pub since: u64,
+}
+
+/// Entities added, modified or removed in an open drawing after `?since=`
+pub async fn get_document_changes(
+    State(state): State<Arc<AppState>>,
+    Path(document_id): Path<Uuid>,
+    Query(params): Query<ChangesQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let drawings = state
+        .drawings
+        .clone()
+        .ok_or_else(|| ApiError::service_unavailable("Open drawings are not shared"))?;
+    let drawings = drawings.lock().await;
+    let drawing = drawings.get(document_id).ok_or_else(|| {
+        ApiError::not_found("document", format!("Document {} is not open", document_id))
+    })?;
+    Ok(ApiResponse::success(
+        drawing.context.document.change_set(params.since),
+        "Document changes retrieved successfully",
+    ))
+}
+
+fn share_error(error: ShareError) -> ApiError {
+    match error {
+        ShareError::NotFound => ApiError::not_found("share", error.to_string()),
+        ShareError::Expired(_) | ShareError::Revoked(_) => {
+            ApiError::new(StatusCode::GONE, "SHARE_LINK_GONE", "Share Link Gone", error.to_string())
+        }
+        ShareError::InvalidExpiry(_) => ApiError::validation_error(vec![FieldError::new(
+            "expires_at",
+            "INVALID_EXPIRY",
+            error.to_string(),
+        )]),
+    }
+}
+
+// ============================================================================
+// Feature Flag Rollout Handlers (admin only)
+// ============================================================================
+
+/// Rollout to define for a feature flag
+#[derive(Debug, Deserialize)]
+pub struct CreateFlagRolloutRequest {
+    pub key: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub percentage: u8,
+    #[serde(default)]
+    pub cohorts: Vec<Cohort>,
+    #[serde(default)]
+    pub excluded: Vec<TenantId>,
+}
+
+/// Change to a flag rollout, with the reason recorded in the audit trail
+#[derive(Debug, Deserialize)]
+pub struct UpdateFlagRolloutRequest {
+    #[serde(flatten)]
+    pub update: FlagUpdate,
+    pub reason: Option<String>,
+}
+
+/// Reason for a kill switch change or removal
+#[derive(Debug, Default, Deserialize)]
+pub struct FlagChangeReason {
+    pub reason: Option<String>,
+}
+
+/// Tenant to evaluate a flag for
+#[derive(Debug, Deserialize)]
+pub struct EvaluateFlagRequest {
+    pub tenant_id: TenantId,
+    /// Custom configuration values used by attribute cohorts
+    #[serde(default)]
+    pub attributes: HashMap<String, serde_json::Value>,
+    /// The tenant's configured value
+    #[serde(default)]
+    pub baseline: bool,
+}
+
+/// List all flag rollouts
+pub async fn list_flag_rollouts(
+    State(state): State<Arc<AppState>>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_admin(user_ctx)?;
+    Ok(ApiResponse::success(
+        state.flag_rollouts.list_flags(),
+        "Flag rollouts retrieved successfully",
+    ))
+}
+
+/// Define a rollout for a flag
+pub async fn create_flag_rollout(
+    State(state): State<Arc<AppState>>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+    Json(request): Json<CreateFlagRolloutRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let admin = require_admin(user_ctx)?;
+    let mut flag = FlagRollout::new(request.key, request.description);
+    flag.percentage = request.percentage;
+    flag.cohorts = request.cohorts;
+    flag.excluded = request.excluded;
+
+    let flag = state
+        .flag_rollouts
+        .create_flag(flag, &admin.user_id)
+        .map_err(rollout_error)?;
+    Ok((
+        StatusCode::CREATED,
+        ApiResponse::success(flag, "Flag rollout created successfully"),
+    ))
+}
+
+/// Get a flag rollout
+pub async fn get_flag_rollout(
+    State(state): State<Arc<AppState>>,
+    Path(key): Path<String>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_admin(user_ctx)?;
+    let flag = state.flag_rollouts.get_flag(&key).map_err(rollout_error)?;
+    Ok(ApiResponse::success(flag, "Flag rollout retrieved successfully"))
+}
+
+/// Change a rollout's percentage, cohorts, exclusions or description
+pub async fn update_flag_rollout(
+    State(state): State<Arc<AppState>>,
+    Path(key): Path<String>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+    Json(request): Json<UpdateFlagRolloutRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let admin = require_admin(user_ctx)?;
+    let flag = state
+        .flag_rollouts
+        .update_flag(&key, request.update, &admin.user_id, request.reason)
+        .map_err(rollout_error)?;
+    Ok(ApiResponse::success(flag, "Flag rollout updated successfully"))
+}
+
+/// Remove a rollout; tenants go back to their configured value
+pub async fn delete_flag_rollout(
+    State(state): State<Arc<AppState>>,
+    Path(key): Path<String>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+    body: Option<Json<FlagChangeReason>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let admin = require_admin(user_ctx)?;
+    let reason = body.and_then(|Json(body)| body.reason);
+    state
+        .flag_rollouts
+        .remove_flag(&key, &admin.user_id, reason)
+        .map_err(rollout_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Turn a feature off for every tenant
+pub async fn engage_flag_kill_switch(
+    State(state): State<Arc<AppState>>,
+    Path(key): Path<String>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+    body: Option<Json<FlagChangeReason>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let admin = require_admin(user_ctx)?;
+    let reason = body.and_then(|Json(body)| body.reason);
+    let flag = state
+        .flag_rollouts
+        .engage_kill_switch(&key, &admin.user_id, reason)
+        .map_err(rollout_error)?;
+    Ok(ApiResponse::success(flag, "Kill switch engaged"))
+}
+
+/// Resume a rollout stopped by its kill switch
+pub async fn release_flag_kill_switch(
+    State(state): State<Arc<AppState>>,
+    Path(key): Path<String>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+    body: Option<Json<FlagChangeReason>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let admin = require_admin(user_ctx)?;
+    let reason = body.and_then(|Json(body)| body.reason);
+    let flag = state
+        .flag_rollouts
+        .release_kill_switch(&key, &admin.user_id, reason)
+        .map_err(rollout_error)?;
+    Ok(ApiResponse::success(flag, "Kill switch released"))
+}
+
+/// Change history of a flag, newest first
+pub async fn get_flag_audit(
+    State(state): State<Arc<AppState>>,
+    Path(key): Path<String>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_admin(user_ctx)?;
+    let changes: Vec<FlagChange> = state.flag_rollouts.audit_log(Some(&key));
+    Ok(ApiResponse::success(changes, "Flag audit trail retrieved successfully"))
+}
+
+/// Which side of a rollout a tenant is on, and why
+pub async fn evaluate_flag_rollout(
+    State(state): State<Arc<AppState>>,
+    Path(key): Path<String>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+    Json(request): Json<EvaluateFlagRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_admin(user_ctx)?;
+    let mut config = TenantConfig::new(request.tenant_id.clone());
+    config.custom = request.attributes;
+    let decision = state
+        .flag_rollouts
+        .evaluate(&key, &request.tenant_id, &config, request.baseline);
+    Ok(ApiResponse::success(decision, "Flag evaluated successfully"))
+}
+
+fn require_admin(user_ctx: Option<axum::Extension<UserContext>>) -> Result<UserContext, ApiError> {
+    match user_ctx {
+        Some(axum::Extension(ctx)) if ctx.has_role("admin") => Ok(ctx),
+        Some(_) => Err(ApiError::forbidden("This endpoint is restricted to administrators")),
+        None => Err(ApiError::unauthorized("Authentication required")),
+    }
+}
+
+fn rollout_error(error: RolloutError) -> ApiError {
+    match error {
+        RolloutError::NotFound(key) => {
+            ApiError::not_found(format!("admin/flags/{}", key), "Flag rollout not found")
+        }
+        RolloutError::AlreadyExists(key) => {
+            ApiError::conflict(format!("A rollout for flag '{}' already exists", key))
+        }
+        RolloutError::InvalidPercentage(_) => ApiError::validation_error(vec![FieldError::new(
+            "percentage",
+            "INVALID_PERCENTAGE",
+            error.to_string(),
+        )]),
+    }
+}
+
+// ============================================================================
+// Admin Console Handlers (admin only)
+// ============================================================================
+
+/// A deployment configuration and the problems found in it
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigValidationResponse {
+    pub config: DeploymentConfig,
+    pub valid: bool,
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ConfigValidationResponse {
+    fn new(config: DeploymentConfig) -> Self {
+        let report = config.validate();
+        Self {
+            config,
+            valid: report.is_valid(),
+            issues: report.issues,
+        }
+    }
+}
+
+/// Background job backlogs and the scheduler health they imply
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobBacklogResponse {
+    pub backlogs: Vec<JobBacklog>,
+    pub thresholds: BacklogThresholds,
+    pub scheduler: ComponentHealth,
+}
+
+/// Maintenance window to start
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnterMaintenanceRequest {
+    pub message: String,
+    /// Expected end, sent to held clients as `Retry-After`
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+/// Health of this node's cluster, database, cache and scheduler
+pub async fn get_node_status(
+    State(state): State<Arc<AppState>>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_admin(user_ctx)?;
+    Ok(ApiResponse::success(
+        state.admin.node_status().await,
+        "Node status retrieved successfully",
+    ))
+}
+
+/// The running configuration, checked for problems
+pub async fn get_admin_config(
+    State(state): State<Arc<AppState>>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_admin(user_ctx)?;
+    Ok(ApiResponse::success(
+        ConfigValidationResponse::new(state.admin.config()),
+        "Configuration validated",
+    ))
+}
+
+/// Check a configuration before rolling it out
+pub async fn validate_admin_config(
+    user_ctx: Option<axum::Extension<UserContext>>,
+    Json(config): Json<DeploymentConfig>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_admin(user_ctx)?;
+    Ok(ApiResponse::success(
+        ConfigValidationResponse::new(config),
+        "Configuration validated",
+    ))
+}
+
+/// Waiting, running and failed background jobs per job type
+pub async fn list_job_backlogs(
+    State(state): State<Arc<AppState>>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_admin(user_ctx)?;
+    let backlogs = state.admin.job_backlogs().await;
+    let thresholds = state.admin.thresholds();
+    let scheduler = thresholds.assess(&backlogs);
+    Ok(ApiResponse::success(
+        JobBacklogResponse {
+            backlogs,
+            thresholds,
+            scheduler,
+        },
+        "Job backlogs retrieved successfully",
+    ))
+}
+
+/// The active maintenance window, if any
+pub async fn get_maintenance(
+    State(state): State<Arc<AppState>>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_admin(user_ctx)?;
+    let window: Option<MaintenanceWindow> = state.admin.maintenance();
+    Ok(ApiResponse::success(window, "Maintenance status retrieved successfully"))
+}
+
+/// Start holding writes
+pub async fn enter_maintenance(
+    State(state): State<Arc<AppState>>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+    Json(request): Json<EnterMaintenanceRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let admin = require_admin(user_ctx)?;
+    if request.message.trim().is_empty() {
+        return Err(ApiError::validation_error(vec![FieldError::new(
+            "message",
+            "REQUIRED",
+            "Tell users why writes are held",
+        )]));
+    }
+    if request.ends_at.is_some_and(|end| end <= Utc::now()) {
+        return Err(ApiError::validation_error(vec![FieldError::new(
+            "endsAt",
+            "IN_PAST",
+            "The maintenance window must end in the future",
+        )]));
+    }
+    let window = state
+        .admin
+        .enter_maintenance(request.message, admin.user_id, request.ends_at);
+    tracing::warn!("Maintenance mode entered by {}: {}", window.started_by, window.message);
+    Ok(ApiResponse::success(window, "Maintenance mode entered"))
+}
+
+/// Stop holding writes
+pub async fn exit_maintenance(
+    State(state): State<Arc<AppState>>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let admin = require_admin(user_ctx)?;
+    let window = state.admin.exit_maintenance().ok_or_else(|| {
+        ApiError::not_found("admin/maintenance".to_string(), "Maintenance mode is not active")
+    })?;
+    tracing::warn!("Maintenance mode ended by {}", admin.user_id);
+    Ok(ApiResponse::success(window, "Maintenance mode ended"))
+}
+
+// ============================================================================
+// License Seat Handlers (admin only)
+// ============================================================================
+
+/// Report window; defaults to the current month
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeatReportQuery {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// Reason for keeping an idle seat
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DismissReclamationRequest {
+    pub note: Option<String>,
+}
+
+/// Per-seat activity, concurrency peaks and reclamation suggestions
+pub async fn get_seat_report(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SeatReportQuery>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_admin(user_ctx)?;
+    let seats = seat_manager(&state)?;
+    let now = Utc::now();
+    let month = ReportPeriod::month_of(now);
+    let period = ReportPeriod::new(
+        query.start.unwrap_or(month.start),
+        query.end.unwrap_or(month.end),
+    );
+    if period.end <= period.start {
+        return Err(ApiError::validation_error(vec![FieldError::new(
+            "end",
+            "INVALID_RANGE",
+            "The report must end after it starts",
+        )]));
+    }
+    let report = seats.read().await.report(period, now);
+    Ok(ApiResponse::success(report, "Seat report generated successfully"))
+}
+
+/// All reclamation cases, oldest first
+pub async fn list_seat_reclamations(
+    State(state): State<Arc<AppState>>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_admin(user_ctx)?;
+    let seats = seat_manager(&state)?;
+    let cases = seats.read().await.cases().to_vec();
+    Ok(ApiResponse::success(cases, "Reclamation cases retrieved successfully"))
+}
+
+/// Release an idle seat without waiting for its notice period
+pub async fn reclaim_seat(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let admin = require_admin(user_ctx)?;
+    let seats = seat_manager(&state)?;
+    let case = seats
+        .write()
+        .await
+        .reclaim(id, &admin.user_id, Utc::now())
+        .map_err(seat_error)?;
+    tracing::info!("Seat of {} reclaimed by {}", case.user_id, admin.user_id);
+    Ok(ApiResponse::success(case, "Seat reclaimed"))
+}
+
+/// Keep an idle seat and close its reclamation case
+pub async fn dismiss_seat_reclamation(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+    body: Option<Json<DismissReclamationRequest>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let admin = require_admin(user_ctx)?;
+    let seats = seat_manager(&state)?;
+    let note = body.and_then(|Json(body)| body.note);
+    let case = seats
+        .write()
+        .await
+        .dismiss(id, &admin.user_id, note, Utc::now())
+        .map_err(seat_error)?;
+    Ok(ApiResponse::success(case, "Reclamation dismissed"))
+}
+
+fn seat_manager(state: &AppState) -> Result<&Arc<RwLock<SeatManager>>, ApiError> {
+    state
+        .seats
+        .as_ref()
+        .ok_or_else(|| ApiError::service_unavailable("License seat tracking is not configured"))
+}
+
+fn seat_error(error: SeatError) -> ApiError {
+    match error {
+        SeatError::CaseNotFound(id) => ApiError::not_found(
+            format!("admin/licenses/seats/reclamations/{}", id),
+            "Reclamation case not found",
+        ),
+        SeatError::CaseClosed(_) => ApiError::conflict(error.to_string()),
+    }
+}
+
+// ============================================================================
+// Project Report Handlers
+// ============================================================================
+
+/// Report to generate; defaults to the standard project documentation as
+/// a PDF
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectReportRequest {
+    pub template: Option<crate::sheets::ReportTemplate>,
+    pub format: Option<crate::sheets::ReportFormat>,
+}
+
+/// Report to generate on a schedule
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleReportRequest {
+    #[serde(flatten)]
+    pub report: ProjectReportRequest,
+    pub schedule: JobSchedule,
+}
+
+/// Generate a project report and return the file
+pub async fn generate_project_report(
+    State(state): State<Arc<AppState>>,
+    Path(project): Path<String>,
+    Json(body): Json<ProjectReportRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let reports = project_reports(&state)?;
+    let format = body.format.unwrap_or(crate::sheets::ReportFormat::Pdf);
+    let template = body
+        .template
+        .unwrap_or_else(crate::sheets::ReportTemplate::project_documentation);
+    let request = reports
+        .request(&project, template, format)
+        .await
+        .map_err(|e| report_error(&project, e))?;
+    let file_name = format!("{}.{}", project, format.extension());
+    let bytes = reports
+        .generate(request)
+        .await
+        .map_err(|e| report_error(&project, e))?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.mime_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        bytes,
+    ))
+}
+
+/// A project's scheduled reports, oldest first
+pub async fn list_report_schedules(
+    State(state): State<Arc<AppState>>,
+    Path(project): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let reports = project_reports(&state)?;
+    let jobs = reports.schedules(&project).await;
+    Ok(ApiResponse::success(jobs, "Report schedules retrieved successfully"))
+}
+
+/// Schedule a project report
+pub async fn schedule_project_report(
+    State(state): State<Arc<AppState>>,
+    Path(project): Path<String>,
+    Json(body): Json<ScheduleReportRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let reports = project_reports(&state)?;
+    let format = body.report.format.unwrap_or(crate::sheets::ReportFormat::Pdf);
+    let template = body
+        .report
+        .template
+        .unwrap_or_else(crate::sheets::ReportTemplate::project_documentation);
+    let request = reports
+        .request(&project, template, format)
+        .await
+        .map_err(|e| report_error(&project, e))?;
+    let job = reports
+        .schedule(&project, &request, body.schedule)
+        .await
+        .map_err(|e| schedule_error(&project, e))?;
+    Ok((
+        StatusCode::CREATED,
+        ApiResponse::success(job, "Report scheduled"),
+    ))
+}
+
+/// Cancel a scheduled project report
+pub async fn cancel_report_schedule(
+    State(state): State<Arc<AppState>>,
+    Path((project, job_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let reports = project_reports(&state)?;
+    reports
+        .cancel(&project, &job_id)
+        .await
+        .map_err(|e| schedule_error(&project, e))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn project_reports(state: &AppState) -> Result<&Arc<ProjectReports>, ApiError> {
+    state
+        .reports
+        .as_ref()
+        .ok_or_else(|| ApiError::service_unavailable("Project reports are not configured"))
+}
+
+fn report_error(project: &str, error: SheetError) -> ApiError {
+    match error {
+        SheetError::NotFound(detail) => {
+            ApiError::not_found(format!("projects/{}", project), detail)
+        }
+        SheetError::Template(detail) => ApiError::validation_error(vec![FieldError::new(
+            "template",
+            "INVALID_TEMPLATE",
+            detail,
+        )]),
+        other => ApiError::internal_error(other.to_string()),
+    }
+}
+
+fn schedule_error(project: &str, error: SchedulerError) -> ApiError {
+    match error {
+        SchedulerError::JobNotFound(id) => ApiError::not_found(
+            format!("projects/{}/reports/schedules/{}", project, id),
+            "Report schedule not found",
+        ),
+        SchedulerError::InvalidCronExpression(detail) => {
+            ApiError::validation_error(vec![FieldError::new("schedule", "INVALID_CRON", detail)])
+        }
+        other => ApiError::internal_error(other.to_string()),
+    }
+}
+
+// ============================================================================
+// Time Tracking Handlers
+// ============================================================================
+
+/// Filter for time entries; other users' entries need the admin role
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeEntriesQuery {
+    pub user: Option<String>,
+    pub project: Option<String>,
+    pub document: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// `json` (default) or `csv`
+    pub format: Option<String>,
+}
+
+/// Time missed by the tracker
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManualTimeEntryRequest {
+    #[serde(flatten)]
+    pub document: TrackedDocument,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub note: Option<String>,
+}
+
+/// Week to summarise; defaults to the current week
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyTimesheetQuery {
+    pub week_of: Option<DateTime<Utc>>,
+    pub user: Option<String>,
+    /// `json` (default) or `csv`
+    pub format: Option<String>,
+}
+
+/// Report editing activity by the calling user in a drawing
+pub async fn record_time_activity(
+    State(state): State<Arc<AppState>>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+    Json(document): Json<TrackedDocument>,
+) -> Result<impl IntoResponse, ApiError> {
+    let tracker = time_tracker(&state)?;
+    let user = require_user(user_ctx)?;
+    let closed = tracker.record_activity(&user.user_id, &document, Utc::now());
+    Ok(ApiResponse::success(closed, "Activity recorded"))
+}
+
+/// Time entries, oldest first, as JSON or CSV
+pub async fn list_time_entries(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<TimeEntriesQuery>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+) -> Result<axum::response::Response, ApiError> {
+    let tracker = time_tracker(&state)?;
+    let user = require_user(user_ctx)?;
+    let user_id = timesheet_owner(&user, params.user)?;
+    let entries = tracker.entries(&TimeQuery {
+        user_id,
+        project_id: params.project,
+        document_id: params.document,
+        since: params.since,
+        until: params.until,
+    });
+
+    if csv_requested(params.format.as_deref())? {
+        return Ok(csv_attachment("time-entries.csv", entries_csv(&entries)));
+    }
+    Ok(ApiResponse::success(entries, "Time entries retrieved successfully").into_response())
+}
+
+/// Add time for the calling user by hand
+pub async fn create_time_entry(
+    State(state): State<Arc<AppState>>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+    Json(request): Json<ManualTimeEntryRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let tracker = time_tracker(&state)?;
+    let user = require_user(user_ctx)?;
+    let entry = tracker
+        .add_manual_entry(&user.user_id, request.document, request.start, request.end, request.note)
+        .map_err(time_error)?;
+    Ok((
+        StatusCode::CREATED,
+        ApiResponse::success(entry, "Time entry created"),
+    ))
+}
+
+/// Correct one of the caller's entries, or anyone's as an admin
+pub async fn adjust_time_entry(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+    Json(adjustment): Json<TimeAdjustment>,
+) -> Result<impl IntoResponse, ApiError> {
+    let tracker = time_tracker(&state)?;
+    let user = require_user(user_ctx)?;
+    owned_time_entry(tracker, &user, id)?;
+    let entry = tracker
+        .adjust_entry(id, adjustment, &user.user_id)
+        .map_err(time_error)?;
+    Ok(ApiResponse::success(entry, "Time entry adjusted"))
+}
+
+/// Remove one of the caller's entries, or anyone's as an admin
+pub async fn delete_time_entry(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let tracker = time_tracker(&state)?;
+    let user = require_user(user_ctx)?;
+    owned_time_entry(tracker, &user, id)?;
+    tracker.remove_entry(id).map_err(time_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Weekly timesheet by user, project and drawing, as JSON or CSV
+pub async fn get_weekly_timesheet(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<WeeklyTimesheetQuery>,
+    user_ctx: Option<axum::Extension<UserContext>>,
+) -> Result<axum::response::Response, ApiError> {
+    let tracker = time_tracker(&state)?;
+    let user = require_user(user_ctx)?;
+    let user_id = timesheet_owner(&user, params.user)?;
+    let summary = tracker.weekly_summary(params.week_of.unwrap_or_else(Utc::now), user_id.as_deref());
+
+    if csv_requested(params.format.as_deref())? {
+        let file_name = format!("timesheet-{}.csv", summary.week_start.format("%Y-%m-%d"));
+        return Ok(csv_attachment(&file_name, summary.to_csv()));
+    }
+    Ok(ApiResponse::success(summary, "Weekly timesheet generated successfully").into_response())
+}
+
+fn time_tracker(state: &AppState) -> Result<&Arc<TimeTracker>, ApiError> {
+    state
+        .time_tracking
+        .as_ref()
+        .filter(|tracker| tracker.is_enabled())
+        .ok_or_else(|| ApiError::service_unavailable("Time tracking is not enabled"))
+}
+
+fn require_user(user_ctx: Option<axum::Extension<UserContext>>) -> Result<UserContext, ApiError> {
+    user_ctx
+        .map(|axum::Extension(ctx)| ctx)
+        .ok_or_else(|| ApiError::unauthorized("Authentication required"))
+}
+
+/// User whose time a request may see: the caller's own unless an admin
+/// asks for someone else's or for everyone's
+fn timesheet_owner(user: &UserContext, requested: Option<String>) -> Result<Option<String>, ApiError> {
+    if user.has_role("admin") {
+        return Ok(requested);
+    }
+    match requested {
+        Some(other) if other != user.user_id => {
+            Err(ApiError::forbidden("Only administrators can see other users' time"))
+        }
+        _ => Ok(Some(user.user_id.clone())),
+    }
+}
+
+fn owned_time_entry(tracker: &TimeTracker, user: &UserContext, id: Uuid) -> Result<(), ApiError> {
+    let entry = tracker.get_entry(id).ok_or_else(|| time_error(TimeTrackingError::EntryNotFound(id)))?;
+    if entry.user_id != user.user_id && !user.has_role("admin") {
+        return Err(ApiError::forbidden("Only administrators can change other users' time"));
+    }
+    Ok(())
+}
+
+fn csv_requested(format: Option<&str>) -> Result<bool, ApiError> {
+    match format.unwrap_or("json") {
+        "json" => Ok(false),
+        "csv" => Ok(true),
+        other => Err(ApiError::validation_error(vec![FieldError::new(
+            "format",
+            "INVALID_FORMAT",
+            format!("Unknown format '{}'; use json or csv", other),
+        )])),
+    }
+}
+
+fn csv_attachment(file_name: &str, body: String) -> axum::response::Response {
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+fn time_error(error: TimeTrackingError) -> ApiError {
+    match error {
+        TimeTrackingError::EntryNotFound(id) => {
+            ApiError::not_found(format!("time/entries/{}", id), "Time entry not found")
+        }
+        TimeTrackingError::InvalidRange { .. } => ApiError::validation_error(vec![FieldError::new(
+            "end",
+            "INVALID_RANGE",
+            error.to_string(),
+        )]),
+        TimeTrackingError::Overlap(_) => ApiError::conflict(error.to_string()),
+        TimeTrackingError::Disabled => ApiError::service_unavailable(error.to_string()),
+    }
+}
+
 // ============================================================================
 // Health Check Handler
 // ============================================================================
@@ -668,7 +1982,7 @@ pub async fn health_check(
     let health = HealthResponse {
         status: HealthStatus::Healthy,
         version: env!("CARGO_PKG_VERSION").to_string(),
-        uptime_seconds: 0, // TODO: Track actual uptime
+        uptime_seconds: state.admin.uptime_seconds(),
         checks,
         timestamp: Utc::now(),
     };
@@ -0,0 +1,248 @@
+//! # JSONPath Selection
+//!
+//! A small JSONPath implementation for webhook payload filters and
+//! templates. It covers the subset those need:
+//!
+//! - `$` - the document root
+//! - `.name` and `['name']` - object member
+//! - `[2]` and `[-1]` - array element, negative indices counting from the end
+//! - `.*` and `[*]` - every member or element
+//! - `..name` and `..*` - recursive descent
+//!
+//! Filter expressions (`[?(...)]`) and slices are not supported.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+/// Parsed JSONPath expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPath {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Child(String),
+    Index(i64),
+    Wildcard,
+    /// Recursive descent, to a named member or (`None`) to everything
+    Descend(Option<String>),
+}
+
+impl JsonPath {
+    /// Parse a path such as `$.data.issues[0].severity`
+    pub fn parse(path: &str) -> Result<Self, JsonPathError> {
+        let chars: Vec<char> = path.trim().chars().collect();
+        let error = |position: usize, reason: &str| JsonPathError::Invalid {
+            path: path.to_string(),
+            position,
+            reason: reason.to_string(),
+        };
+        if chars.first() != Some(&'$') {
+            return Err(error(0, "must start with '$'"));
+        }
+
+        let name_end = |from: usize| {
+            (from..chars.len())
+                .find(|&i| chars[i] == '.' || chars[i] == '[')
+                .unwrap_or(chars.len())
+        };
+        let mut segments = Vec::new();
+        let mut i = 1;
+        while i < chars.len() {
+            match chars[i] {
+                '.' if chars.get(i + 1) == Some(&'.') => {
+                    let end = name_end(i + 2);
+                    let name: String = chars[i + 2..end].iter().collect();
+                    match name.as_str() {
+                        "" => return Err(error(i, "expected a name after '..'")),
+                        "*" => segments.push(Segment::Descend(None)),
+                        _ => segments.push(Segment::Descend(Some(name))),
+                    }
+                    i = end;
+                }
+                '.' => {
+                    let end = name_end(i + 1);
+                    let name: String = chars[i + 1..end].iter().collect();
+                    match name.as_str() {
+                        "" => return Err(error(i, "expected a name after '.'")),
+                        "*" => segments.push(Segment::Wildcard),
+                        _ => segments.push(Segment::Child(name)),
+                    }
+                    i = end;
+                }
+                '[' => {
+                    let close = (i + 1..chars.len())
+                        .find(|&j| chars[j] == ']')
+                        .ok_or_else(|| error(i, "unclosed '['"))?;
+                    let inner: String = chars[i + 1..close].iter().collect();
+                    let inner = inner.trim();
+                    let quoted = inner.len() >= 2
+                        && ((inner.starts_with('\'') && inner.ends_with('\''))
+                            || (inner.starts_with('"') && inner.ends_with('"')));
+                    if inner == "*" {
+                        segments.push(Segment::Wildcard);
+                    } else if quoted {
+                        segments.push(Segment::Child(inner[1..inner.len() - 1].to_string()));
+                    } else {
+                        let index = inner
+                            .parse()
+                            .map_err(|_| error(i + 1, "expected an index, '*' or a quoted name"))?;
+                        segments.push(Segment::Index(index));
+                    }
+                    i = close + 1;
+                }
+                _ => return Err(error(i, "expected '.' or '['")),
+            }
+        }
+
+        Ok(Self {
+            source: chars.iter().collect(),
+            segments,
+        })
+    }
+
+    /// Values the path selects in `document`, in document order
+    pub fn select<'a>(&self, document: &'a Value) -> Vec<&'a Value> {
+        let mut current = vec![document];
+        for segment in &self.segments {
+            current = match segment {
+                Segment::Child(name) => current.into_iter().filter_map(|v| v.get(name.as_str())).collect(),
+                Segment::Index(index) => current
+                    .into_iter()
+                    .filter_map(|v| {
+                        let items = v.as_array()?;
+                        let index = if *index < 0 { items.len() as i64 + index } else { *index };
+                        items.get(usize::try_from(index).ok()?)
+                    })
+                    .collect(),
+                Segment::Wildcard => current.into_iter().flat_map(children).collect(),
+                Segment::Descend(name) => {
+                    let mut all = Vec::new();
+                    for v in current {
+                        descendants(v, &mut all);
+                    }
+                    match name {
+                        Some(name) => all.into_iter().filter_map(|v| v.get(name.as_str())).collect(),
+                        None => all.into_iter().flat_map(children).collect(),
+                    }
+                }
+            };
+        }
+        current
+    }
+
+    /// First selected value, if any
+    pub fn first<'a>(&self, document: &'a Value) -> Option<&'a Value> {
+        self.select(document).into_iter().next()
+    }
+
+    /// The path as written
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+fn children(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Object(map) => map.values().collect(),
+        Value::Array(items) => items.iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `value` and everything below it, parents first
+fn descendants<'a>(value: &'a Value, out: &mut Vec<&'a Value>) {
+    out.push(value);
+    for child in children(value) {
+        descendants(child, out);
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = JsonPathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl Serialize for JsonPath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonPath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Self::parse(&source).map_err(serde::de::Error::custom)
+    }
+}
+
+/// JSONPath parse error
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum JsonPathError {
+    #[error("Invalid JSONPath {path:?} at {position}: {reason}")]
+    Invalid {
+        path: String,
+        position: usize,
+        reason: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn document() -> Value {
+        json!({
+            "type": "issue_detected",
+            "data": {
+                "issues": [
+                    {"severity": "critical", "rule": "contrast"},
+                    {"severity": "minor", "rule": "alt-text"}
+                ],
+                "site": {"name": "docs", "tags": ["public"]}
+            }
+        })
+    }
+
+    #[test]
+    fn test_select_members_and_indices() {
+        let doc = document();
+        let select = |path: &str| JsonPath::parse(path).unwrap().select(&doc);
+
+        assert_eq!(select("$.type"), vec![&json!("issue_detected")]);
+        assert_eq!(select("$['data']['site'].name"), vec![&json!("docs")]);
+        assert_eq!(select("$.data.issues[0].severity"), vec![&json!("critical")]);
+        assert_eq!(select("$.data.issues[-1].rule"), vec![&json!("alt-text")]);
+        assert_eq!(select("$.data.issues[*].severity").len(), 2);
+        assert_eq!(select("$..severity").len(), 2);
+        assert_eq!(select("$..tags[0]"), vec![&json!("public")]);
+        assert!(select("$.data.missing").is_empty());
+        assert!(select("$.data.issues[5]").is_empty());
+        assert_eq!(select("$"), vec![&doc]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for path in ["data.type", "$.", "$..", "$[0", "$[abc]", "$x"] {
+            assert!(JsonPath::parse(path).is_err(), "{path}");
+        }
+
+        let path: JsonPath = serde_json::from_value(json!("$.data.site")).unwrap();
+        assert_eq!(serde_json::to_value(&path).unwrap(), json!("$.data.site"));
+        assert!(serde_json::from_value::<JsonPath>(json!("nope")).is_err());
+    }
+}
@@ -7,7 +7,8 @@
 //! - Structured request/response logging with tracing
 //! - CORS configuration for cross-origin requests
 //! - Request validation and sanitization
-//! - Request ID tracking for distributed tracing
+//! - Request ID tracking and W3C trace context for distributed tracing
+//! - Maintenance mode that holds writes while an admin works on the node
 //! - Performance metrics collection
 //!
 //! # Examples
@@ -41,6 +42,9 @@ use crate::enterprise::auth::{JwtManager, TokenClaims, User};
 use crate::enterprise::ratelimit::{
     QuotaIdentifier, QuotaLimits, QuotaPeriod, RateLimiter, RateLimiterConfig,
 };
+use crate::enterprise::tracing::propagation::{self, TRACEPARENT_HEADER, TRACESTATE_HEADER};
+use crate::enterprise::tracing::SpanContext;
+use super::admin::AdminConsole;
 use super::responses::ApiError;
 
 // ============================================================================
@@ -77,6 +81,34 @@ pub async fn request_id_middleware(
     response
 }
 
+// ============================================================================
+// Trace Context Middleware
+// ============================================================================
+
+/// Continue the caller's W3C trace, or start one, for the request
+///
+/// The server span context is stored in request extensions and made current
+/// for the handler, so webhook and integration deliveries it triggers are
+/// traced as its children. The response carries it back in `traceparent`.
+pub async fn trace_context_middleware(
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let value = |name: &str| request.headers().get(name).and_then(|v| v.to_str().ok());
+    let context = propagation::extract(value(TRACEPARENT_HEADER), value(TRACESTATE_HEADER))
+        .map(|parent| parent.child())
+        .unwrap_or_else(SpanContext::new_root);
+
+    request.extensions_mut().insert(context.clone());
+    let mut response = propagation::in_context(context.clone(), next.run(request)).await;
+
+    if let Ok(header_value) = HeaderValue::from_str(&context.to_traceparent()) {
+        response.headers_mut().insert(TRACEPARENT_HEADER, header_value);
+    }
+
+    response
+}
+
 // ============================================================================
 // Authentication Middleware
 // ============================================================================
@@ -524,6 +556,42 @@ pub async fn security_headers_middleware(
     response
 }
 
+// ============================================================================
+// Maintenance Mode Middleware
+// ============================================================================
+
+/// Hold writes with `503 Service Unavailable` during a maintenance window
+///
+/// Reads keep working so users can still open and export drawings, and
+/// admins and the admin endpoints pass through so the window can be ended.
+/// Must run inside [`auth_middleware`] to see the caller's roles.
+pub async fn maintenance_middleware(
+    State(console): State<Arc<AdminConsole>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(window) = console.maintenance() else {
+        return next.run(request).await;
+    };
+
+    let read_only = matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    let admin_path = request.uri().path().contains("/admin/");
+    let is_admin = request
+        .extensions()
+        .get::<UserContext>()
+        .is_some_and(|ctx| ctx.has_role("admin"));
+    if read_only || admin_path || is_admin {
+        return next.run(request).await;
+    }
+
+    let retry_after = window.retry_after(chrono::Utc::now());
+    let mut response = ApiError::service_unavailable(window.message).into_response();
+    if let Ok(value) = HeaderValue::from_str(&retry_after.to_string()) {
+        response.headers_mut().insert(header::RETRY_AFTER, value);
+    }
+    response
+}
+
 // ============================================================================
 // Middleware Stack Builder
 // ============================================================================
@@ -537,6 +605,7 @@ pub fn build_middleware_stack(
 
     let mut stack = ServiceBuilder::new()
         .layer(from_fn(request_id_middleware))
+        .layer(from_fn(trace_context_middleware))
         .layer(from_fn(security_headers_middleware))
         .layer(from_fn(request_logging_middleware))
         .layer(from_fn(content_type_validation_middleware))
@@ -8,22 +8,43 @@
 //! - **API Gateway**: Circuit breaker, retry logic, and load balancing
 //! - **Middleware**: Authentication, rate limiting, logging, CORS
 //! - **Standardized Responses**: HAL, JSON:API, and RFC 7807 support
-//! - **Webhook System**: Event-driven integrations with retry and verification
+//! - **Webhook System**: Event-driven integrations with retry, verification,
+//!   payload filters and templates, and delivery replay
 //! - **Request Handlers**: Comprehensive handlers for all resources
+//! - **Share Links**: Public, expiring review links to document revisions
+//!   for the web viewer, revocable and logged in the access history
 //!
 //! ## Quick Start
 //!
 //! ```rust,ignore
 //! use caddy::api::*;
+//! use caddy::enterprise::collaboration::ShareLinkManager;
+//! use caddy::enterprise::compliance::AccessHistory;
+//! use caddy::api::admin::{AdminConsole, DeploymentConfig};
+//! use caddy::enterprise::tenant::RolloutManager;
+//! use caddy::io::trash::RecycleBin;
 //! use std::sync::Arc;
+//! use tokio::sync::RwLock;
 //!
 //! #[tokio::main]
 //! async fn main() {
 //!     // Configure application
 //!     let app_config = AppConfig::default();
+//!     let access_history = AccessHistory::new();
 //!     let app_state = Arc::new(AppState {
 //!         db_pool: Arc::new(()),
 //!         config: Arc::new(app_config),
+//!         trash: Arc::new(RwLock::new(RecycleBin::default())),
+//!         webhooks: WebhookManager::new(),
+//!         access_history: access_history.clone(),
+//!         share_links: ShareLinkManager::new(access_history),
+//!         flag_rollouts: Arc::new(RolloutManager::new()),
+//!         admin: Arc::new(AdminConsole::new(DeploymentConfig::from_env())),
+//!         seats: None,
+//!         reports: None,
+//!         time_tracking: None,
+//!         previews: None,
+//!         drawings: None,
 //!     });
 //!
 //!     // Configure authentication
@@ -86,6 +107,65 @@
 //! - `PUT /api/v1/webhooks/:id` - Update webhook
 //! - `DELETE /api/v1/webhooks/:id` - Delete webhook
 //! - `POST /api/v1/webhooks/:id/test` - Test webhook
+//! - `GET /api/v1/webhooks/:id/deliveries` - Delivery history
+//! - `POST /api/v1/webhooks/:id/deliveries/:delivery_id/replay` - Replay a delivery
+//!
+//! ### Recycle Bin
+//! - `GET /api/v1/trash` - List deleted entities and documents
+//! - `GET /api/v1/trash/:id` - Get recycle bin item
+//! - `POST /api/v1/trash/:id/restore` - Restore item
+//!
+//! ### Documents
+//! - `GET /api/v1/documents/:id/access` - Access history
+//! - `POST /api/v1/documents/:id/access` - Record an access
+//! - `GET /api/v1/documents/:id/access/summary` - Access summary
+//! - `GET /api/v1/documents/:id/shares` - Share links, newest first
+//! - `POST /api/v1/documents/:id/shares` - Create a review link to a revision
+//! - `DELETE /api/v1/documents/:id/shares/:link_id` - Revoke a share link
+//! - `GET /api/v1/documents/:id/thumbnail?size=` - PNG preview, small, medium or large
+//! - `GET /api/v1/documents/:id/blocks/:name/thumbnail?size=` - PNG preview of a block
+//! - `GET /api/v1/documents/:id/changes?since=` - Entities changed in an open drawing
+//! - `GET /share/:token` - Open a share link (public)
+//!
+//! ### Feature Flag Rollouts (admin)
+//! - `GET /api/v1/admin/flags` - List rollouts
+//! - `POST /api/v1/admin/flags` - Define a rollout
+//! - `GET /api/v1/admin/flags/:key` - Get a rollout
+//! - `PATCH /api/v1/admin/flags/:key` - Change percentage, cohorts or exclusions
+//! - `DELETE /api/v1/admin/flags/:key` - Remove a rollout
+//! - `POST /api/v1/admin/flags/:key/kill` - Engage the kill switch
+//! - `POST /api/v1/admin/flags/:key/release` - Release the kill switch
+//! - `GET /api/v1/admin/flags/:key/audit` - Change history
+//! - `POST /api/v1/admin/flags/:key/evaluate` - Evaluate for a tenant
+//!
+//! ### Admin Console
+//! - `GET /api/v1/admin/status` - Cluster, database, cache and scheduler health
+//! - `GET /api/v1/admin/config` - Running configuration, validated
+//! - `POST /api/v1/admin/config/validate` - Validate a proposed configuration
+//! - `GET /api/v1/admin/jobs` - Background job backlogs
+//! - `GET /api/v1/admin/maintenance` - Active maintenance window
+//! - `PUT /api/v1/admin/maintenance` - Enter maintenance mode (writes get 503)
+//! - `DELETE /api/v1/admin/maintenance` - Leave maintenance mode
+//!
+//! ### License Seats
+//! - `GET /api/v1/admin/licenses/seats` - Seat usage report with reclamation suggestions
+//! - `GET /api/v1/admin/licenses/seats/reclamations` - Idle-seat reclamation cases
+//! - `POST /api/v1/admin/licenses/seats/reclamations/:id/reclaim` - Release an idle seat now
+//! - `POST /api/v1/admin/licenses/seats/reclamations/:id/dismiss` - Keep an idle seat
+//!
+//! ### Project Reports
+//! - `POST /api/v1/projects/:project/reports` - Generate a report now (PDF, HTML or Markdown)
+//! - `GET /api/v1/projects/:project/reports/schedules` - Scheduled reports
+//! - `POST /api/v1/projects/:project/reports/schedules` - Schedule a report
+//! - `DELETE /api/v1/projects/:project/reports/schedules/:job_id` - Cancel a scheduled report
+//!
+//! ### Time Tracking
+//! - `POST /api/v1/time/activity` - Report editing activity in a drawing
+//! - `GET /api/v1/time/entries` - Time entries (`format=csv` for a file)
+//! - `POST /api/v1/time/entries` - Add time by hand
+//! - `PATCH /api/v1/time/entries/:id` - Correct an entry
+//! - `DELETE /api/v1/time/entries/:id` - Remove an entry
+//! - `GET /api/v1/time/summary/weekly` - Weekly timesheet (`format=csv` for a file)
 //!
 //! ## Architecture
 //!
@@ -220,6 +300,12 @@ pub mod gateway;
 /// Webhook system with event dispatching
 pub mod webhooks;
 
+/// JSONPath selection for webhook filters and templates
+pub mod jsonpath;
+
+/// Admin console: node status, config validation, job backlogs, maintenance
+pub mod admin;
+
 // ============================================================================
 // Re-exports for Convenience
 // ============================================================================
@@ -233,8 +319,8 @@ pub use responses::{
 // Middleware types and functions
 pub use middleware::{
     auth_middleware, cors_layer, cors_layer_with_origins, rate_limit_middleware,
-    request_id_middleware, request_logging_middleware, require_any_role, require_role,
-    security_headers_middleware, AuthConfig, RateLimitConfig, UserContext,
+    maintenance_middleware, request_id_middleware, request_logging_middleware, require_any_role,
+    require_role, security_headers_middleware, AuthConfig, RateLimitConfig, UserContext,
 };
 
 // Handler types
@@ -256,8 +342,15 @@ pub use gateway::{
 
 // Webhook types
 pub use webhooks::{
-    verify_signature, DeliveryStatus, EventType, Webhook, WebhookDelivery, WebhookError,
-    WebhookEvent, WebhookManager, WebhookStats,
+    verify_signature, DeliveryStatus, EventType, FilterOp, PayloadFilter, PayloadTemplate,
+    Webhook, WebhookDelivery, WebhookError, WebhookEvent, WebhookManager, WebhookStats,
+};
+pub use jsonpath::{JsonPath, JsonPathError};
+
+// Admin console types
+pub use admin::{
+    AdminConsole, BacklogSource, BacklogThresholds, ConfigIssue, ConfigReport, ConfigSeverity,
+    DeploymentConfig, HealthProbe, JobBacklog, MaintenanceWindow, NodeStatus,
 };
 
 // ============================================================================
@@ -368,9 +461,21 @@ impl Default for ApiServerBuilder {
 
 /// Create default application state
 pub fn create_default_app_state() -> Arc<AppState> {
+    let access_history = crate::enterprise::compliance::AccessHistory::new();
     Arc::new(AppState {
         db_pool: Arc::new(()),
         config: Arc::new(AppConfig::default()),
+        trash: Arc::new(tokio::sync::RwLock::new(crate::io::trash::RecycleBin::default())),
+        webhooks: WebhookManager::new(),
+        access_history: access_history.clone(),
+        share_links: crate::enterprise::collaboration::ShareLinkManager::new(access_history),
+        flag_rollouts: Arc::new(crate::enterprise::tenant::RolloutManager::new()),
+        admin: Arc::new(admin::AdminConsole::new(admin::DeploymentConfig::from_env())),
+        seats: None,
+        reports: None,
+        time_tracking: None,
+        previews: None,
+        drawings: None,
     })
 }
 
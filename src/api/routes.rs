@@ -10,6 +10,12 @@
 //! - `/api/v1/sites` - Site/project management
 //! - `/api/v1/settings` - Configuration endpoints
 //! - `/api/v1/webhooks` - Webhook management
+//! - `/api/v1/trash` - Recycle bin
+//! - `/api/v1/documents` - Document access history, share links, thumbnails and entity changes
+//! - `/api/v1/admin/flags` - Feature flag rollouts
+//! - `/api/v1/admin` - Node status, configuration, job backlogs and maintenance mode
+//! - `/api/v1/projects` - Project documentation reports
+//! - `/api/v1/time` - Time tracking entries and weekly timesheets
 //!
 //! ## Examples
 //!
@@ -29,13 +35,13 @@ use std::sync::Arc;
 
 use super::handlers::*;
 use super::middleware::{
-    auth_middleware, cors_layer, rate_limit_middleware,
+    auth_middleware, cors_layer, maintenance_middleware, rate_limit_middleware,
     request_id_middleware, request_logging_middleware,
     security_headers_middleware, AuthConfig, RateLimitConfig,
 };
 use super::webhooks::{
-    create_webhook, delete_webhook, list_webhooks, test_webhook,
-    trigger_webhook_test, update_webhook,
+    create_webhook, delete_webhook, get_webhook, list_webhook_deliveries, list_webhooks,
+    replay_webhook_delivery, test_webhook, trigger_webhook_test, update_webhook,
 };
 
 // ============================================================================
@@ -61,8 +67,22 @@ pub fn create_v1_router(
         .nest("/settings", settings_routes())
         // Webhook routes
         .nest("/webhooks", webhooks_routes())
+        // Recycle bin routes
+        .nest("/trash", trash_routes())
+        // Document routes
+        .nest("/documents", documents_routes())
+        // Feature flag rollout routes
+        .nest("/admin/flags", flag_rollout_routes())
+        // Admin console routes
+        .nest("/admin", admin_routes())
+        // Project report routes
+        .nest("/projects", project_routes())
+        // Time tracking routes
+        .nest("/time", time_routes())
         // Health check
         .route("/health", get(health_check))
+        // Hold writes during maintenance; needs the user context from auth
+        .layer(from_fn_with_state(app_state.admin.clone(), maintenance_middleware))
         // Apply authentication middleware to protected routes
         .layer(from_fn_with_state(auth_config.clone(), auth_middleware))
         // Apply rate limiting
@@ -185,11 +205,123 @@ fn webhooks_routes() -> Router<Arc<AppState>> {
         // Test webhook
         .route("/:id/test", post(test_webhook))
         // Get webhook delivery logs
-        .route("/:id/deliveries", get(get_webhook_deliveries))
+        .route("/:id/deliveries", get(list_webhook_deliveries))
+        // Redeliver a past event
+        .route(
+            "/:id/deliveries/:delivery_id/replay",
+            post(replay_webhook_delivery),
+        )
         // Trigger test webhook (system use)
         .route("/trigger/:id", post(trigger_webhook_test))
 }
 
+/// Recycle bin routes
+fn trash_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        // List deleted entities and documents
+        .route("/", get(list_trash))
+        // Get specific item
+        .route("/:id", get(get_trash_item))
+        // Restore item
+        .route("/:id/restore", post(restore_trash_item))
+}
+
+/// Document routes
+fn documents_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        // Access history, newest first
+        .route("/:id/access", get(list_document_access))
+        // Record a view, edit, export or share
+        .route("/:id/access", post(record_document_access))
+        // Access counts and recent users
+        .route("/:id/access/summary", get(get_document_access_summary))
+        // Review links to a revision
+        .route("/:id/shares", get(list_share_links))
+        .route("/:id/shares", post(create_share_link))
+        .route("/:id/shares/:link_id", delete(revoke_share_link))
+        // PNG previews, `?size=small|medium|large`
+        .route("/:id/thumbnail", get(get_document_thumbnail))
+        .route("/:id/blocks/:name/thumbnail", get(get_block_thumbnail))
+        // Entities changed in an open drawing, `?since=<version>`
+        .route("/:id/changes", get(get_document_changes))
+}
+
+/// Feature flag rollout routes
+fn flag_rollout_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        // List rollouts
+        .route("/", get(list_flag_rollouts))
+        // Define a rollout
+        .route("/", post(create_flag_rollout))
+        // Get a rollout
+        .route("/:key", get(get_flag_rollout))
+        // Change percentage, cohorts or exclusions
+        .route("/:key", patch(update_flag_rollout))
+        // Remove a rollout
+        .route("/:key", delete(delete_flag_rollout))
+        // Kill switch
+        .route("/:key/kill", post(engage_flag_kill_switch))
+        .route("/:key/release", post(release_flag_kill_switch))
+        // Change history
+        .route("/:key/audit", get(get_flag_audit))
+        // Which side a tenant is on
+        .route("/:key/evaluate", post(evaluate_flag_rollout))
+}
+
+/// Admin console routes
+fn admin_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        // Component health
+        .route("/status", get(get_node_status))
+        // Running configuration, checked
+        .route("/config", get(get_admin_config))
+        // Check a proposed configuration
+        .route("/config/validate", post(validate_admin_config))
+        // Background job backlogs
+        .route("/jobs", get(list_job_backlogs))
+        // Maintenance mode
+        .route("/maintenance", get(get_maintenance))
+        .route("/maintenance", put(enter_maintenance))
+        .route("/maintenance", delete(exit_maintenance))
+        // License seat usage
+        .route("/licenses/seats", get(get_seat_report))
+        // Idle-seat reclamation
+        .route("/licenses/seats/reclamations", get(list_seat_reclamations))
+        .route("/licenses/seats/reclamations/:id/reclaim", post(reclaim_seat))
+        .route(
+            "/licenses/seats/reclamations/:id/dismiss",
+            post(dismiss_seat_reclamation),
+        )
+}
+
+/// Project report routes
+fn project_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        // Generate a report now
+        .route("/:project/reports", post(generate_project_report))
+        // Scheduled reports
+        .route("/:project/reports/schedules", get(list_report_schedules))
+        .route("/:project/reports/schedules", post(schedule_project_report))
+        .route(
+            "/:project/reports/schedules/:job_id",
+            delete(cancel_report_schedule),
+        )
+}
+
+/// Time tracking routes
+fn time_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        // Editing activity from a client
+        .route("/activity", post(record_time_activity))
+        // Entries, as JSON or CSV
+        .route("/entries", get(list_time_entries))
+        .route("/entries", post(create_time_entry))
+        .route("/entries/:id", patch(adjust_time_entry))
+        .route("/entries/:id", delete(delete_time_entry))
+        // Timesheet for a week
+        .route("/summary/weekly", get(get_weekly_timesheet))
+}
+
 // ============================================================================
 // Public Routes (No Authentication Required)
 // ============================================================================
@@ -203,6 +335,8 @@ pub fn create_public_router(app_state: Arc<AppState>) -> Router {
         .route("/docs", get(api_documentation))
         // OpenAPI spec
         .route("/openapi.json", get(openapi_spec))
+        // Share links for the web viewer; the token is the credential
+        .route("/share/:token", get(open_share_link))
         .with_state(app_state)
 }
 
@@ -504,27 +638,6 @@ async fn revoke_api_key(
     Ok(StatusCode::NO_CONTENT)
 }
 
-async fn get_webhook(
-    State(_state): State<Arc<AppState>>,
-    Path(webhook_id): Path<String>,
-) -> Result<impl IntoResponse, ApiError> {
-    Err(ApiError::not_found(
-        format!("webhooks/{}", webhook_id),
-        "Webhook not found",
-    ))
-}
-
-async fn get_webhook_deliveries(
-    State(_state): State<Arc<AppState>>,
-    Path(_webhook_id): Path<String>,
-) -> Result<impl IntoResponse, ApiError> {
-    Ok(PaginatedResponse::new(
-        Vec::<serde_json::Value>::new(),
-        0,
-        PaginationMeta::offset(1, 20, 0),
-    ))
-}
-
 async fn api_documentation(
     State(_state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
@@ -595,3 +708,45 @@ mod tests {
         assert_eq!(ApiVersion::V2.path(), "/api/v2");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::DocumentSet;
+    use crate::io::document::Document;
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_document_changes_route() {
+        let mut drawings = DocumentSet::new();
+        let id = drawings.open(Document::new(), "Plan", None);
+        let drawing = &mut drawings.get_mut(id).unwrap().context.document;
+        drawing.add_entity(Box::new(1u32));
+        let seen = drawing.version();
+        let added = drawing.add_entity(Box::new(2u32));
+
+        let mut state = (*crate::api::create_default_app_state()).clone();
+        state.drawings = Some(Arc::new(tokio::sync::Mutex::new(drawings)));
+        let app = documents_routes().with_state(Arc::new(state));
+
+        let request = Request::get(format!("/{}/changes?since={}", id, seen))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let changes = json["data"]["changes"].as_array().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0]["id"], added.0);
+        assert_eq!(json["data"]["version"], seen + 1);
+
+        let request = Request::get(format!("/{}/changes", uuid::Uuid::new_v4()))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -6,8 +6,16 @@
 //! - **Event Dispatching**: Deliver events to registered webhooks
 //! - **Retry Logic**: Automatic retry with exponential backoff
 //! - **Signature Verification**: HMAC-SHA256 signature for security
-//! - **Delivery Tracking**: Track delivery attempts and status
-//! - **Event Filtering**: Subscribe to specific event types
+//! - **Delivery Tracking**: Track delivery attempts and status, each traced
+//!   as a child span of the API call that raised the event
+//! - **Event Filtering**: Subscribe to specific event types, narrowed by
+//!   JSONPath conditions on the payload
+//! - **Payload Templates**: Reshape the payload per endpoint, e.g. into a
+//!   Slack-compatible message
+//! - **Replay**: Redeliver any event from the delivery history
+//! - **Auto-Disable**: Endpoints that keep failing are switched off
+//! - **Payload Contracts**: Events are validated against the schema
+//!   registered for their type before delivery
 //! - **Rate Limiting**: Prevent webhook spam
 //! - **Batch Delivery**: Group multiple events for efficient delivery
 //!
@@ -55,11 +63,19 @@ use axum::{
     Json,
 };
 
+use crate::enterprise::eventsource::schema::SchemaRegistry;
+use crate::enterprise::tracing::propagation::{self, DeliverySpan};
+use crate::enterprise::tracing::SpanContext;
+
 use super::handlers::AppState;
+use super::jsonpath::JsonPath;
 use super::responses::{ApiError, ApiResponse, PaginatedResponse, PaginationMeta};
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Deliveries kept per webhook; the oldest are dropped first
+pub const MAX_DELIVERY_HISTORY: usize = 500;
+
 // ============================================================================
 // Webhook Types
 // ============================================================================
@@ -88,6 +104,18 @@ pub struct Webhook {
     #[serde(default)]
     pub headers: HashMap<String, String>,
 
+    /// Payload conditions, all of which an event must meet
+    #[serde(default)]
+    pub filters: Vec<PayloadFilter>,
+
+    /// Shape of the delivered payload
+    #[serde(default)]
+    pub template: PayloadTemplate,
+
+    /// Why the webhook was switched off automatically
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disabled_reason: Option<String>,
+
     /// Webhook description
     pub description: Option<String>,
 
@@ -116,6 +144,24 @@ pub struct WebhookStats {
 
     /// Average response time in milliseconds
     pub avg_response_time_ms: u64,
+
+    /// Failed deliveries since the last success
+    #[serde(default)]
+    pub consecutive_failures: u32,
+}
+
+impl Webhook {
+    /// Whether `event` should be delivered to this webhook
+    pub fn accepts(&self, event: &WebhookEvent) -> bool {
+        if !self.active || !self.events.contains(&event.event_type) {
+            return false;
+        }
+        if self.filters.is_empty() {
+            return true;
+        }
+        let document = serde_json::to_value(event).unwrap_or_default();
+        self.filters.iter().all(|filter| filter.matches(&document))
+    }
 }
 
 /// Event types that can trigger webhooks
@@ -157,6 +203,16 @@ impl EventType {
             EventType::SiteDeleted,
         ]
     }
+
+    /// Schema registry subject for payloads of this type, e.g.
+    /// `webhook.scan_completed`
+    pub fn schema_subject(&self) -> String {
+        let name = serde_json::to_value(self)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        format!("webhook.{}", name)
+    }
 }
 
 /// Webhook event payload
@@ -217,6 +273,22 @@ pub struct WebhookDelivery {
 
     /// Next retry time (if applicable)
     pub next_retry_at: Option<DateTime<Utc>>,
+
+    /// Trace the delivery span belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+
+    /// Delivery span, sent downstream as the `traceparent` parent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span_id: Option<String>,
+
+    /// Delivery this one manually replayed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay_of: Option<String>,
+
+    /// Event delivered, kept for replay
+    #[serde(skip)]
+    pub event: Option<WebhookEvent>,
 }
 
 /// Delivery status
@@ -233,6 +305,257 @@ pub enum DeliveryStatus {
     Cancelled,
 }
 
+// ============================================================================
+// Payload Filters and Templates
+// ============================================================================
+
+/// Condition on the event payload, e.g. `{"path": "$.data.severity",
+/// "op": "in", "value": ["critical", "serious"]}`
+///
+/// The path is evaluated against the event as delivered (`$.type`,
+/// `$.data...`). When it selects several values, the condition holds if any
+/// of them meets it (for `notEquals`, if none is equal).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadFilter {
+    /// JSONPath into the event
+    pub path: JsonPath,
+
+    /// Comparison
+    pub op: FilterOp,
+
+    /// Operand; required by every operator except `exists`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+}
+
+/// Payload filter comparison
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FilterOp {
+    /// The path selects anything
+    Exists,
+    /// Equal to the operand
+    Equals,
+    /// Not equal to the operand
+    NotEquals,
+    /// One of the operand array's elements
+    In,
+    /// String containing the operand, or array containing it as an element
+    Contains,
+    /// Number greater than the operand
+    GreaterThan,
+    /// Number less than the operand
+    LessThan,
+}
+
+impl PayloadFilter {
+    /// Filter with an operand
+    pub fn new(path: JsonPath, op: FilterOp, value: serde_json::Value) -> Self {
+        Self {
+            path,
+            op,
+            value: Some(value),
+        }
+    }
+
+    /// Filter requiring `path` to select something
+    pub fn exists(path: JsonPath) -> Self {
+        Self {
+            path,
+            op: FilterOp::Exists,
+            value: None,
+        }
+    }
+
+    /// Check that the operand suits the operator
+    pub fn validate(&self) -> Result<(), WebhookError> {
+        let invalid = |reason: &str| {
+            Err(WebhookError::InvalidFilter(format!("{}: {}", self.path, reason)))
+        };
+        match (self.op, &self.value) {
+            (FilterOp::Exists, _) => Ok(()),
+            (_, None) => invalid("missing value"),
+            (FilterOp::In, Some(value)) if !value.is_array() => invalid("'in' needs an array"),
+            (FilterOp::Contains, Some(value)) if value.is_object() || value.is_array() => {
+                invalid("'contains' needs a scalar")
+            }
+            (FilterOp::GreaterThan | FilterOp::LessThan, Some(value)) if !value.is_number() => {
+                invalid("comparison needs a number")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the condition holds for `document`
+    pub fn matches(&self, document: &serde_json::Value) -> bool {
+        let selected = self.path.select(document);
+        let Some(operand) = &self.value else {
+            return self.op == FilterOp::Exists && !selected.is_empty();
+        };
+        let compare = |ordering: std::cmp::Ordering| {
+            selected.iter().any(|value| {
+                match (value.as_f64(), operand.as_f64()) {
+                    (Some(value), Some(operand)) => value.partial_cmp(&operand) == Some(ordering),
+                    _ => false,
+                }
+            })
+        };
+
+        match self.op {
+            FilterOp::Exists => !selected.is_empty(),
+            FilterOp::Equals => selected.contains(&operand),
+            FilterOp::NotEquals => selected.iter().all(|value| *value != operand),
+            FilterOp::In => operand
+                .as_array()
+                .is_some_and(|options| selected.iter().any(|value| options.contains(value))),
+            FilterOp::Contains => selected.iter().any(|value| match (value, operand) {
+                (serde_json::Value::String(text), serde_json::Value::String(part)) => {
+                    text.contains(part.as_str())
+                }
+                (serde_json::Value::Array(items), _) => items.contains(operand),
+                _ => false,
+            }),
+            FilterOp::GreaterThan => compare(std::cmp::Ordering::Greater),
+            FilterOp::LessThan => compare(std::cmp::Ordering::Less),
+        }
+    }
+}
+
+/// Shape of the payload sent to an endpoint
+///
+/// Template strings reference the event with `{{$.path}}` placeholders. A
+/// string that is a single placeholder takes the selected value as is, so
+/// numbers and objects keep their type; otherwise selected values are
+/// interpolated as text and missing ones as nothing.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum PayloadTemplate {
+    /// The event itself
+    #[default]
+    Event,
+
+    /// Slack incoming-webhook message
+    Slack {
+        /// Message text (Slack `mrkdwn`)
+        #[serde(default = "default_slack_text")]
+        text: String,
+    },
+
+    /// Arbitrary JSON body
+    Custom {
+        /// Body with placeholders in its strings
+        body: serde_json::Value,
+    },
+}
+
+fn default_slack_text() -> String {
+    "*{{$.type}}* event `{{$.id}}` at {{$.timestamp}}".to_string()
+}
+
+impl PayloadTemplate {
+    /// Slack message with the default text
+    pub fn slack() -> Self {
+        Self::Slack {
+            text: default_slack_text(),
+        }
+    }
+
+    /// Check that every placeholder is a valid path
+    pub fn validate(&self) -> Result<(), WebhookError> {
+        let mut strings = Vec::new();
+        match self {
+            Self::Event => {}
+            Self::Slack { text } => strings.push(text.as_str()),
+            Self::Custom { body } => collect_strings(body, &mut strings),
+        }
+        for text in strings {
+            for (_, path) in placeholders(text) {
+                path.map_err(|err| WebhookError::InvalidTemplate(err.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Payload for `event`
+    pub fn render(&self, event: &WebhookEvent) -> serde_json::Value {
+        let document = serde_json::to_value(event).unwrap_or_default();
+        match self {
+            Self::Event => document,
+            Self::Slack { text } => serde_json::json!({
+                "text": render_string(text, &document),
+            }),
+            Self::Custom { body } => render_value(body, &document),
+        }
+    }
+}
+
+/// `{{...}}` placeholders in `text` with their byte ranges
+fn placeholders(
+    text: &str,
+) -> Vec<(std::ops::Range<usize>, Result<JsonPath, super::jsonpath::JsonPathError>)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(open) = text[from..].find("{{").map(|i| from + i) {
+        let Some(close) = text[open + 2..].find("}}").map(|i| open + 2 + i) else {
+            break;
+        };
+        found.push((open..close + 2, JsonPath::parse(&text[open + 2..close])));
+        from = close + 2;
+    }
+    found
+}
+
+fn collect_strings<'a>(value: &'a serde_json::Value, out: &mut Vec<&'a str>) {
+    match value {
+        serde_json::Value::String(text) => out.push(text),
+        serde_json::Value::Array(items) => items.iter().for_each(|item| collect_strings(item, out)),
+        serde_json::Value::Object(map) => map.values().for_each(|item| collect_strings(item, out)),
+        _ => {}
+    }
+}
+
+fn render_value(template: &serde_json::Value, document: &serde_json::Value) -> serde_json::Value {
+    match template {
+        serde_json::Value::String(text) => render_string(text, document),
+        serde_json::Value::Array(items) => {
+            items.iter().map(|item| render_value(item, document)).collect()
+        }
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(key, item)| (key.clone(), render_value(item, document)))
+            .collect(),
+        other => other.clone(),
+    }
+}
+
+/// Render one template string; invalid placeholders are left as written
+fn render_string(text: &str, document: &serde_json::Value) -> serde_json::Value {
+    let found = placeholders(text);
+    if let [(range, Ok(path))] = found.as_slice() {
+        if range.start == 0 && range.end == text.len() {
+            return path.first(document).cloned().unwrap_or_default();
+        }
+    }
+
+    let mut rendered = String::with_capacity(text.len());
+    let mut last = 0;
+    for (range, path) in found {
+        let Ok(path) = path else {
+            continue;
+        };
+        rendered.push_str(&text[last..range.start]);
+        match path.first(document) {
+            Some(serde_json::Value::String(value)) => rendered.push_str(value),
+            Some(serde_json::Value::Null) | None => {}
+            Some(value) => rendered.push_str(&value.to_string()),
+        }
+        last = range.end;
+    }
+    rendered.push_str(&text[last..]);
+    serde_json::Value::String(rendered)
+}
+
 // ============================================================================
 // Webhook Manager
 // ============================================================================
@@ -250,6 +573,9 @@ pub struct WebhookManager {
 
     /// Retry configuration
     retry_config: RetryConfig,
+
+    /// Payload contracts checked before dispatch
+    schemas: Option<Arc<SchemaRegistry>>,
 }
 
 /// Retry configuration
@@ -266,6 +592,10 @@ pub struct RetryConfig {
 
     /// Backoff multiplier
     pub backoff_multiplier: f64,
+
+    /// Failed deliveries in a row, each after all its retries, before the
+    /// webhook is disabled; 0 never disables
+    pub disable_after_failures: u32,
 }
 
 impl Default for RetryConfig {
@@ -275,6 +605,7 @@ impl Default for RetryConfig {
             initial_delay: Duration::from_secs(30),
             max_delay: Duration::from_secs(3600),
             backoff_multiplier: 2.0,
+            disable_after_failures: 10,
         }
     }
 }
@@ -290,9 +621,22 @@ impl WebhookManager {
                 .build()
                 .unwrap(),
             retry_config: RetryConfig::default(),
+            schemas: None,
         }
     }
 
+    /// Use `retry_config` for deliveries
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    /// Validate events against `schemas` before dispatch
+    pub fn with_schema_registry(mut self, schemas: Arc<SchemaRegistry>) -> Self {
+        self.schemas = Some(schemas);
+        self
+    }
+
     /// Register a new webhook
     pub async fn register_webhook(
         &self,
@@ -300,10 +644,7 @@ impl WebhookManager {
         events: Vec<EventType>,
         secret: Option<String>,
     ) -> Result<Webhook, WebhookError> {
-        // Validate URL
-        if !url.starts_with("http://") && !url.starts_with("https://") {
-            return Err(WebhookError::InvalidUrl);
-        }
+        validate_url(&url)?;
 
         let webhook = Webhook {
             id: Uuid::new_v4().to_string(),
@@ -312,6 +653,9 @@ impl WebhookManager {
             secret: secret.unwrap_or_else(|| generate_secret()),
             active: true,
             headers: HashMap::new(),
+            filters: Vec::new(),
+            template: PayloadTemplate::Event,
+            disabled_reason: None,
             description: None,
             created_at: Utc::now(),
             last_delivery_at: None,
@@ -339,6 +683,16 @@ impl WebhookManager {
         webhook_id: &str,
         updates: WebhookUpdate,
     ) -> Result<Webhook, WebhookError> {
+        if let Some(url) = &updates.url {
+            validate_url(url)?;
+        }
+        for filter in updates.filters.iter().flatten() {
+            filter.validate()?;
+        }
+        if let Some(template) = &updates.template {
+            template.validate()?;
+        }
+
         let mut webhooks = self.webhooks.write();
         let webhook = webhooks
             .get_mut(webhook_id)
@@ -352,10 +706,20 @@ impl WebhookManager {
         }
         if let Some(active) = updates.active {
             webhook.active = active;
+            if active {
+                webhook.disabled_reason = None;
+                webhook.stats.consecutive_failures = 0;
+            }
         }
         if let Some(headers) = updates.headers {
             webhook.headers = headers;
         }
+        if let Some(filters) = updates.filters {
+            webhook.filters = filters;
+        }
+        if let Some(template) = updates.template {
+            webhook.template = template;
+        }
         if let Some(description) = updates.description {
             webhook.description = Some(description);
         }
@@ -377,18 +741,34 @@ impl WebhookManager {
     }
 
     /// Dispatch event to all matching webhooks
+    ///
+    /// Only active webhooks subscribed to the event type whose payload
+    /// filters all match receive it. An event that fails the schema
+    /// registered for its type is not delivered at all. Deliveries are
+    /// traced as children of the caller's trace context.
     pub async fn dispatch_event(&self, event: WebhookEvent) {
+        if let Some(schemas) = &self.schemas {
+            let subject = event.event_type.schema_subject();
+            let payload = serde_json::to_value(&event).unwrap_or_default();
+            if let Err(err) = schemas.validate(&subject, &payload) {
+                tracing::warn!(event_id = %event.id, "Webhook event not dispatched: {}", err);
+                return;
+            }
+        }
+
+        let parent = propagation::current_context();
         let webhooks = self.webhooks.read();
 
         for webhook in webhooks.values() {
-            if webhook.active && webhook.events.contains(&event.event_type) {
+            if webhook.accepts(&event) {
                 let webhook = webhook.clone();
                 let event = event.clone();
                 let manager = self.clone();
+                let parent = parent.clone();
 
                 // Spawn delivery task
                 tokio::spawn(async move {
-                    let _ = manager.deliver_to_webhook(&webhook, &event).await;
+                    let _ = manager.deliver_to_webhook(&webhook, &event, parent.as_ref()).await;
                 });
             }
         }
@@ -399,13 +779,14 @@ impl WebhookManager {
         &self,
         webhook: &Webhook,
         event: &WebhookEvent,
+        parent: Option<&SpanContext>,
     ) -> Result<(), WebhookError> {
         let mut retry_count = 0;
         let mut delay = self.retry_config.initial_delay;
 
         loop {
             let delivery = self
-                .attempt_delivery(webhook, event, retry_count)
+                .attempt_delivery(webhook, event, retry_count, parent)
                 .await;
 
             // Record delivery
@@ -419,7 +800,9 @@ impl WebhookManager {
 
             retry_count += 1;
 
-            if retry_count >= self.retry_config.max_attempts {
+            // Give up early if the webhook was disabled or deleted meanwhile
+            let still_active = self.get_webhook(&webhook.id).is_some_and(|w| w.active);
+            if retry_count >= self.retry_config.max_attempts || !still_active {
                 self.update_webhook_stats(&webhook.id, false, delivery.response_time_ms);
                 return Err(WebhookError::DeliveryFailed);
             }
@@ -438,11 +821,20 @@ impl WebhookManager {
         webhook: &Webhook,
         event: &WebhookEvent,
         retry_count: u32,
+        parent: Option<&SpanContext>,
     ) -> WebhookDelivery {
         let start = std::time::Instant::now();
-        let payload = serde_json::to_string(event).unwrap();
+        let payload = webhook.template.render(event).to_string();
         let signature = generate_signature(&webhook.secret, &payload);
 
+        let mut span = DeliverySpan::start("webhook.deliver", parent);
+        span.set_attribute("webhook.id", webhook.id.as_str());
+        span.set_attribute("webhook.event_id", event.id.as_str());
+        span.set_attribute("webhook.event_type", format!("{:?}", event.event_type));
+        span.set_attribute("webhook.retry_count", retry_count as i64);
+        let trace_id = Some(span.context().trace_id.to_hex());
+        let span_id = Some(span.context().span_id.to_hex());
+
         let mut request = self
             .client
             .post(&webhook.url)
@@ -452,6 +844,10 @@ impl WebhookManager {
             .header("X-Webhook-ID", &webhook.id)
             .header("X-Event-ID", &event.id);
 
+        for (name, value) in span.headers() {
+            request = request.header(name, value);
+        }
+
         // Add custom headers
         for (key, value) in &webhook.headers {
             request = request.header(key, value);
@@ -475,6 +871,7 @@ impl WebhookManager {
                 } else {
                     DeliveryStatus::Failed
                 };
+                span.finish(Some(status_code), None);
 
                 WebhookDelivery {
                     id: Uuid::new_v4().to_string(),
@@ -488,31 +885,74 @@ impl WebhookManager {
                     response_time_ms: Some(response_time_ms),
                     retry_count,
                     next_retry_at: None,
+                    trace_id,
+                    span_id,
+                    replay_of: None,
+                    event: Some(event.clone()),
+                }
+            }
+            Err(err) => {
+                span.finish(None, Some(&err.to_string()));
+
+                WebhookDelivery {
+                    id: Uuid::new_v4().to_string(),
+                    webhook_id: webhook.id.clone(),
+                    event_id: event.id.clone(),
+                    status: DeliveryStatus::Failed,
+                    status_code: None,
+                    response_body: None,
+                    error: Some(err.to_string()),
+                    attempted_at: Utc::now(),
+                    response_time_ms: Some(response_time_ms),
+                    retry_count,
+                    next_retry_at: Some(Utc::now() + chrono::Duration::seconds(60)),
+                    trace_id,
+                    span_id,
+                    replay_of: None,
+                    event: Some(event.clone()),
                 }
             }
-            Err(err) => WebhookDelivery {
-                id: Uuid::new_v4().to_string(),
-                webhook_id: webhook.id.clone(),
-                event_id: event.id.clone(),
-                status: DeliveryStatus::Failed,
-                status_code: None,
-                response_body: None,
-                error: Some(err.to_string()),
-                attempted_at: Utc::now(),
-                response_time_ms: Some(response_time_ms),
-                retry_count,
-                next_retry_at: Some(Utc::now() + chrono::Duration::seconds(60)),
-            },
         }
     }
 
-    /// Record delivery attempt
+    /// Redeliver the event of a past delivery, once and without retries
+    ///
+    /// Works on disabled webhooks too, so an endpoint can be checked before
+    /// it is switched back on. The new attempt is added to the history.
+    pub async fn replay_delivery(
+        &self,
+        webhook_id: &str,
+        delivery_id: &str,
+    ) -> Result<WebhookDelivery, WebhookError> {
+        let webhook = self.get_webhook(webhook_id).ok_or(WebhookError::NotFound)?;
+        let event = self
+            .get_delivery(webhook_id, delivery_id)
+            .and_then(|delivery| delivery.event)
+            .ok_or(WebhookError::DeliveryNotFound)?;
+
+        let parent = propagation::current_context();
+        let mut delivery = self.attempt_delivery(&webhook, &event, 0, parent.as_ref()).await;
+        delivery.replay_of = Some(delivery_id.to_string());
+
+        self.record_delivery(webhook.id.clone(), delivery.clone());
+        self.update_webhook_stats(
+            &webhook.id,
+            delivery.status == DeliveryStatus::Success,
+            delivery.response_time_ms,
+        );
+        Ok(delivery)
+    }
+
+    /// Record delivery attempt, dropping the oldest beyond
+    /// [`MAX_DELIVERY_HISTORY`]
     fn record_delivery(&self, webhook_id: String, delivery: WebhookDelivery) {
         let mut deliveries = self.deliveries.write();
-        deliveries
-            .entry(webhook_id)
-            .or_insert_with(Vec::new)
-            .push(delivery);
+        let history = deliveries.entry(webhook_id).or_default();
+        history.push(delivery);
+        if history.len() > MAX_DELIVERY_HISTORY {
+            let excess = history.len() - MAX_DELIVERY_HISTORY;
+            history.drain(..excess);
+        }
     }
 
     /// Update webhook statistics
@@ -522,8 +962,25 @@ impl WebhookManager {
             webhook.stats.total_deliveries += 1;
             if success {
                 webhook.stats.successful_deliveries += 1;
+                webhook.stats.consecutive_failures = 0;
             } else {
                 webhook.stats.failed_deliveries += 1;
+                webhook.stats.consecutive_failures += 1;
+
+                let threshold = self.retry_config.disable_after_failures;
+                if webhook.active && threshold > 0 && webhook.stats.consecutive_failures >= threshold {
+                    webhook.active = false;
+                    webhook.disabled_reason = Some(format!(
+                        "Disabled after {} consecutive failed deliveries",
+                        webhook.stats.consecutive_failures
+                    ));
+                    tracing::warn!(
+                        webhook_id = %webhook.id,
+                        url = %webhook.url,
+                        "Webhook disabled after {} consecutive failed deliveries",
+                        webhook.stats.consecutive_failures
+                    );
+                }
             }
 
             if let Some(time_ms) = response_time_ms {
@@ -538,7 +995,7 @@ impl WebhookManager {
         }
     }
 
-    /// Get delivery history for webhook
+    /// Get delivery history for webhook, oldest first
     pub fn get_deliveries(&self, webhook_id: &str) -> Vec<WebhookDelivery> {
         self.deliveries
             .read()
@@ -546,6 +1003,16 @@ impl WebhookManager {
             .cloned()
             .unwrap_or_default()
     }
+
+    /// Get one delivery
+    pub fn get_delivery(&self, webhook_id: &str, delivery_id: &str) -> Option<WebhookDelivery> {
+        self.deliveries
+            .read()
+            .get(webhook_id)?
+            .iter()
+            .find(|delivery| delivery.id == delivery_id)
+            .cloned()
+    }
 }
 
 impl Clone for WebhookManager {
@@ -555,6 +1022,7 @@ impl Clone for WebhookManager {
             deliveries: self.deliveries.clone(),
             client: self.client.clone(),
             retry_config: self.retry_config.clone(),
+            schemas: self.schemas.clone(),
         }
     }
 }
@@ -569,6 +1037,15 @@ impl Default for WebhookManager {
 // Helper Functions
 // ============================================================================
 
+/// Only HTTP(S) endpoints are accepted
+fn validate_url(url: &str) -> Result<(), WebhookError> {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        Ok(())
+    } else {
+        Err(WebhookError::InvalidUrl)
+    }
+}
+
 /// Generate webhook secret
 fn generate_secret() -> String {
     use rand::Rng;
@@ -624,6 +1101,26 @@ pub enum WebhookError {
 
     #[error("Invalid signature")]
     InvalidSignature,
+
+    #[error("Webhook delivery not found")]
+    DeliveryNotFound,
+
+    #[error("Invalid payload filter: {0}")]
+    InvalidFilter(String),
+
+    #[error("Invalid payload template: {0}")]
+    InvalidTemplate(String),
+}
+
+impl From<WebhookError> for ApiError {
+    fn from(err: WebhookError) -> Self {
+        match err {
+            WebhookError::NotFound => ApiError::not_found("webhooks", err.to_string()),
+            WebhookError::DeliveryNotFound => ApiError::not_found("deliveries", err.to_string()),
+            WebhookError::DeliveryFailed => ApiError::internal_error(err.to_string()),
+            _ => ApiError::bad_request(err.to_string()),
+        }
+    }
 }
 
 // ============================================================================
@@ -631,14 +1128,17 @@ pub enum WebhookError {
 // ============================================================================
 
 /// Webhook update request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WebhookUpdate {
     pub url: Option<String>,
     pub events: Option<Vec<EventType>>,
+    /// Setting `true` also re-enables an automatically disabled webhook
     pub active: Option<bool>,
     pub headers: Option<HashMap<String, String>>,
     pub description: Option<String>,
+    pub filters: Option<Vec<PayloadFilter>>,
+    pub template: Option<PayloadTemplate>,
 }
 
 /// Create webhook request
@@ -649,35 +1149,70 @@ pub struct CreateWebhookRequest {
     pub events: Vec<EventType>,
     pub secret: Option<String>,
     pub description: Option<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub filters: Vec<PayloadFilter>,
+    #[serde(default)]
+    pub template: PayloadTemplate,
+}
+
+/// Delivery history query
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDeliveriesQuery {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+    pub status: Option<DeliveryStatus>,
 }
 
 /// List webhooks handler
 pub async fn list_webhooks(
-    State(_state): State<Arc<AppState>>,
+    State(state): State<Arc<AppState>>,
 ) -> Result<impl IntoResponse, ApiError> {
-    // TODO: Get from actual webhook manager
-    let webhooks: Vec<Webhook> = vec![];
+    let mut webhooks = state.webhooks.list_webhooks();
+    webhooks.sort_by_key(|webhook| webhook.created_at);
     Ok(ApiResponse::success(webhooks, "Webhooks retrieved"))
 }
 
+/// Get webhook handler
+pub async fn get_webhook(
+    State(state): State<Arc<AppState>>,
+    Path(webhook_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let webhook = state.webhooks.get_webhook(&webhook_id).ok_or_else(|| {
+        ApiError::not_found(format!("webhooks/{}", webhook_id), "Webhook not found")
+    })?;
+    Ok(ApiResponse::success(webhook, "Webhook retrieved"))
+}
+
 /// Create webhook handler
 pub async fn create_webhook(
-    State(_state): State<Arc<AppState>>,
+    State(state): State<Arc<AppState>>,
     Json(request): Json<CreateWebhookRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
-    // TODO: Use actual webhook manager
-    let webhook = Webhook {
-        id: Uuid::new_v4().to_string(),
-        url: request.url,
-        events: request.events,
-        secret: request.secret.unwrap_or_else(generate_secret),
-        active: true,
-        headers: HashMap::new(),
-        description: request.description,
-        created_at: Utc::now(),
-        last_delivery_at: None,
-        stats: WebhookStats::default(),
-    };
+    for filter in &request.filters {
+        filter.validate()?;
+    }
+    request.template.validate()?;
+
+    let webhook = state
+        .webhooks
+        .register_webhook(request.url, request.events, request.secret)
+        .await?;
+    let webhook = state
+        .webhooks
+        .update_webhook(
+            &webhook.id,
+            WebhookUpdate {
+                headers: Some(request.headers),
+                description: request.description,
+                filters: Some(request.filters),
+                template: Some(request.template),
+                ..Default::default()
+            },
+        )
+        .await?;
 
     Ok((
         StatusCode::CREATED,
@@ -687,26 +1222,68 @@ pub async fn create_webhook(
 
 /// Update webhook handler
 pub async fn update_webhook(
-    State(_state): State<Arc<AppState>>,
+    State(state): State<Arc<AppState>>,
     Path(webhook_id): Path<String>,
     Json(update): Json<WebhookUpdate>,
 ) -> Result<impl IntoResponse, ApiError> {
-    // TODO: Update in actual webhook manager
-    Err(ApiError::not_found(
-        format!("webhooks/{}", webhook_id),
-        "Webhook not found",
-    ))
+    let webhook = state.webhooks.update_webhook(&webhook_id, update).await?;
+    Ok(ApiResponse::success(webhook, "Webhook updated"))
 }
 
 /// Delete webhook handler
 pub async fn delete_webhook(
-    State(_state): State<Arc<AppState>>,
-    Path(_webhook_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    Path(webhook_id): Path<String>,
 ) -> Result<StatusCode, ApiError> {
-    // TODO: Delete from actual webhook manager
+    state.webhooks.delete_webhook(&webhook_id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// List webhook deliveries, newest first
+pub async fn list_webhook_deliveries(
+    State(state): State<Arc<AppState>>,
+    Path(webhook_id): Path<String>,
+    Query(params): Query<ListDeliveriesQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    if state.webhooks.get_webhook(&webhook_id).is_none() {
+        return Err(WebhookError::NotFound.into());
+    }
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params
+        .per_page
+        .unwrap_or(state.config.default_page_size)
+        .min(state.config.max_page_size);
+
+    let matching: Vec<WebhookDelivery> = state
+        .webhooks
+        .get_deliveries(&webhook_id)
+        .into_iter()
+        .rev()
+        .filter(|delivery| params.status.is_none_or(|status| delivery.status == status))
+        .collect();
+    let total = matching.len() as u64;
+    let deliveries: Vec<WebhookDelivery> = matching
+        .into_iter()
+        .skip(((page - 1) * per_page) as usize)
+        .take(per_page as usize)
+        .collect();
+
+    let pagination = PaginationMeta::offset(page, per_page, total);
+    Ok(PaginatedResponse::new(deliveries, total, pagination))
+}
+
+/// Replay a past delivery handler
+pub async fn replay_webhook_delivery(
+    State(state): State<Arc<AppState>>,
+    Path((webhook_id, delivery_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let delivery = state
+        .webhooks
+        .replay_delivery(&webhook_id, &delivery_id)
+        .await?;
+    Ok(ApiResponse::success(delivery, "Delivery replayed"))
+}
+
 /// Test webhook handler
 pub async fn test_webhook(
     State(_state): State<Arc<AppState>>,
@@ -823,4 +1400,220 @@ mod tests {
         let webhooks = manager.list_webhooks();
         assert_eq!(webhooks.len(), 2);
     }
+
+    fn event(data: serde_json::Value) -> WebhookEvent {
+        WebhookEvent {
+            id: "evt_1".to_string(),
+            event_type: EventType::IssueDetected,
+            timestamp: Utc::now(),
+            data,
+            attempt: None,
+        }
+    }
+
+    fn path(path: &str) -> JsonPath {
+        JsonPath::parse(path).unwrap()
+    }
+
+    /// Endpoint answering every request with `status`, counting requests
+    async fn endpoint(status: u16) -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buffer = vec![0u8; 8192];
+                let _ = socket.read(&mut buffer).await;
+                counter.fetch_add(1, Ordering::SeqCst);
+                let response = format!(
+                    "HTTP/1.1 {} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        (format!("http://{}/hook", address), hits)
+    }
+
+    #[test]
+    fn test_payload_filters() {
+        let document = serde_json::to_value(event(serde_json::json!({
+            "severity": "critical",
+            "count": 7,
+            "tags": ["wcag", "contrast"],
+        })))
+        .unwrap();
+        let check = |filter: PayloadFilter| {
+            filter.validate().unwrap();
+            filter.matches(&document)
+        };
+
+        assert!(check(PayloadFilter::exists(path("$.data.severity"))));
+        assert!(!check(PayloadFilter::exists(path("$.data.missing"))));
+        assert!(check(PayloadFilter::new(path("$.type"), FilterOp::Equals, "issue_detected".into())));
+        assert!(check(PayloadFilter::new(
+            path("$.data.severity"),
+            FilterOp::In,
+            serde_json::json!(["critical", "serious"]),
+        )));
+        assert!(!check(PayloadFilter::new(path("$.data.severity"), FilterOp::NotEquals, "critical".into())));
+        assert!(check(PayloadFilter::new(path("$.data.tags"), FilterOp::Contains, "wcag".into())));
+        assert!(check(PayloadFilter::new(path("$.data.count"), FilterOp::GreaterThan, 5.into())));
+        assert!(!check(PayloadFilter::new(path("$.data.count"), FilterOp::LessThan, 5.into())));
+
+        assert!(PayloadFilter::new(path("$.data.count"), FilterOp::In, 5.into()).validate().is_err());
+        let parsed: PayloadFilter = serde_json::from_value(serde_json::json!({
+            "path": "$.data.severity",
+            "op": "notEquals",
+            "value": "minor",
+        }))
+        .unwrap();
+        assert!(parsed.matches(&document));
+    }
+
+    #[test]
+    fn test_payload_templates() {
+        let event = event(serde_json::json!({"issue": {"rule": "alt-text", "count": 3}}));
+
+        assert_eq!(PayloadTemplate::Event.render(&event), serde_json::to_value(&event).unwrap());
+
+        let slack = PayloadTemplate::slack().render(&event);
+        let text = slack["text"].as_str().unwrap();
+        assert!(text.starts_with("*issue_detected* event `evt_1` at "));
+
+        let custom = PayloadTemplate::Custom {
+            body: serde_json::json!({
+                "summary": "{{$.data.issue.rule}} x{{$.data.issue.count}}{{$.data.missing}}",
+                "count": "{{$.data.issue.count}}",
+                "issue": "{{ $.data.issue }}",
+                "fixed": [1, "literal"],
+            }),
+        };
+        custom.validate().unwrap();
+        assert_eq!(
+            custom.render(&event),
+            serde_json::json!({
+                "summary": "alt-text x3",
+                "count": 3,
+                "issue": {"rule": "alt-text", "count": 3},
+                "fixed": [1, "literal"],
+            })
+        );
+
+        let invalid = PayloadTemplate::Slack { text: "{{data.rule}}".to_string() };
+        assert!(matches!(invalid.validate(), Err(WebhookError::InvalidTemplate(_))));
+    }
+
+    #[tokio::test]
+    async fn test_accepts_applies_filters() {
+        let manager = WebhookManager::new();
+        let webhook = manager
+            .register_webhook("https://example.com/hook".to_string(), vec![EventType::IssueDetected], None)
+            .await
+            .unwrap();
+        let update = WebhookUpdate {
+            filters: Some(vec![PayloadFilter::new(path("$.data.severity"), FilterOp::Equals, "critical".into())]),
+            ..Default::default()
+        };
+        let webhook = manager.update_webhook(&webhook.id, update).await.unwrap();
+
+        assert!(webhook.accepts(&event(serde_json::json!({"severity": "critical"}))));
+        assert!(!webhook.accepts(&event(serde_json::json!({"severity": "minor"}))));
+
+        let mut other = event(serde_json::json!({"severity": "critical"}));
+        other.event_type = EventType::ScanFailed;
+        assert!(!webhook.accepts(&other));
+    }
+
+    #[tokio::test]
+    async fn test_auto_disable_and_replay() {
+        use std::sync::atomic::Ordering;
+
+        let (failing_url, failing_hits) = endpoint(500).await;
+        let (working_url, _) = endpoint(204).await;
+        let manager = WebhookManager::new().with_retry_config(RetryConfig {
+            max_attempts: 2,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+            backoff_multiplier: 1.0,
+            disable_after_failures: 2,
+        });
+        let webhook = manager
+            .register_webhook(failing_url, vec![EventType::IssueDetected], None)
+            .await
+            .unwrap();
+
+        let event = event(serde_json::json!({"severity": "critical"}));
+        for _ in 0..2 {
+            let result = manager.deliver_to_webhook(&webhook, &event, None).await;
+            assert!(matches!(result, Err(WebhookError::DeliveryFailed)));
+        }
+        assert_eq!(failing_hits.load(Ordering::SeqCst), 4);
+
+        let disabled = manager.get_webhook(&webhook.id).unwrap();
+        assert!(!disabled.active);
+        assert!(disabled.disabled_reason.is_some());
+        assert_eq!(disabled.stats.consecutive_failures, 2);
+        assert!(!disabled.accepts(&event));
+
+        // Point the endpoint somewhere working and replay while still disabled
+        let update = WebhookUpdate {
+            url: Some(working_url),
+            ..Default::default()
+        };
+        manager.update_webhook(&webhook.id, update).await.unwrap();
+        let failed = manager.get_deliveries(&webhook.id).pop().unwrap();
+        let replayed = manager.replay_delivery(&webhook.id, &failed.id).await.unwrap();
+        assert_eq!(replayed.status, DeliveryStatus::Success);
+        assert_eq!(replayed.replay_of.as_deref(), Some(failed.id.as_str()));
+        assert_eq!(replayed.event_id, event.id);
+        assert_eq!(manager.get_deliveries(&webhook.id).len(), 5);
+        assert_eq!(manager.get_webhook(&webhook.id).unwrap().stats.consecutive_failures, 0);
+
+        assert!(matches!(
+            manager.replay_delivery(&webhook.id, "missing").await,
+            Err(WebhookError::DeliveryNotFound)
+        ));
+
+        let update = WebhookUpdate {
+            active: Some(true),
+            ..Default::default()
+        };
+        let enabled = manager.update_webhook(&webhook.id, update).await.unwrap();
+        assert!(enabled.active);
+        assert!(enabled.disabled_reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_rejects_events_breaking_schema() {
+        let schemas = Arc::new(SchemaRegistry::new());
+        schemas
+            .register(
+                &EventType::IssueDetected.schema_subject(),
+                serde_json::json!({
+                    "type": "object",
+                    "required": ["data"],
+                    "properties": {
+                        "data": {"type": "object", "required": ["severity"]}
+                    }
+                }),
+            )
+            .unwrap();
+        let manager = WebhookManager::new().with_schema_registry(schemas.clone());
+        let webhook = manager
+            .register_webhook("http://127.0.0.1:9/hook".to_string(), vec![EventType::IssueDetected], None)
+            .await
+            .unwrap();
+
+        manager.dispatch_event(event(serde_json::json!({"rule": "alt-text"}))).await;
+        tokio::task::yield_now().await;
+
+        let stats = schemas.stats("webhook.issue_detected").unwrap();
+        assert_eq!((stats.validated, stats.rejected), (1, 1));
+        assert!(manager.get_deliveries(&webhook.id).is_empty());
+    }
 }
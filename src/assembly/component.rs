@@ -0,0 +1,206 @@
+//! Assembly components: placed instances of external part files
+
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+
+use nalgebra::Isometry3;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{AssemblyError, AssemblyResult};
+use crate::core::{Point3, Vector3};
+use crate::geometry::mesh::TriangleMesh;
+use crate::io::obj::ObjReader;
+use crate::io::stl::StlReader;
+
+/// File extensions that can be inserted as components
+pub const PART_EXTENSIONS: &[&str] = &["stl", "obj"];
+
+/// STL corners closer than this are welded into one vertex
+const WELD_TOLERANCE: f64 = 1e-9;
+
+/// A part file placed in an assembly
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Component {
+    /// Unique component identifier
+    pub id: Uuid,
+    /// Instance name shown in the assembly tree
+    pub name: String,
+    /// Part file path as stored, relative to the assembly or absolute
+    pub source: String,
+    /// Placement of the part in assembly coordinates
+    pub placement: Isometry3<f64>,
+    /// Grounded components are never moved by the solver
+    pub fixed: bool,
+    /// Part mesh in part coordinates, shared by instances of one file
+    #[serde(skip)]
+    pub(super) geometry: Option<Arc<TriangleMesh>>,
+}
+
+impl Component {
+    /// Component at the assembly origin, geometry not yet loaded
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            source: source.into(),
+            placement: Isometry3::identity(),
+            fixed: false,
+            geometry: None,
+        }
+    }
+
+    /// Set the placement
+    pub fn with_placement(mut self, placement: Isometry3<f64>) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Ground the component so the solver never moves it
+    pub fn fixed(mut self) -> Self {
+        self.fixed = true;
+        self
+    }
+
+    /// Use an in-memory mesh instead of loading the part file
+    pub fn with_geometry(mut self, mesh: TriangleMesh) -> Self {
+        self.geometry = Some(Arc::new(mesh));
+        self
+    }
+
+    /// Loaded part mesh, in part coordinates
+    pub fn geometry(&self) -> Option<&Arc<TriangleMesh>> {
+        self.geometry.as_ref()
+    }
+
+    /// Part point in assembly coordinates
+    pub fn to_assembly_point(&self, point: &Point3) -> Point3 {
+        self.placement * point
+    }
+
+    /// Part direction in assembly coordinates
+    pub fn to_assembly_vector(&self, vector: &Vector3) -> Vector3 {
+        self.placement * vector
+    }
+
+    /// Triangles of the placed part, or `None` before loading
+    pub fn triangles(&self) -> Option<Vec<[Point3; 3]>> {
+        let mesh = self.geometry.as_ref()?;
+        let corner = |i: usize| self.to_assembly_point(&mesh.vertices[i].position);
+        Some(
+            mesh.faces
+                .iter()
+                .map(|f| [corner(f.vertices[0]), corner(f.vertices[1]), corner(f.vertices[2])])
+                .collect(),
+        )
+    }
+
+    /// Axis-aligned bounds of the placed part, or `None` before loading
+    pub fn bounds(&self) -> Option<(Point3, Point3)> {
+        let mesh = self.geometry.as_ref()?;
+        let mut points = mesh.vertices.iter().map(|v| self.to_assembly_point(&v.position));
+        let first = points.next()?;
+        Some(points.fold((first, first), |(min, max), p| (min.inf(&p), max.sup(&p))))
+    }
+}
+
+/// Read a part file as a triangle mesh
+///
+/// STL files are read as ASCII when they start with `solid`, falling back to
+/// binary, since some binary exporters write that word into the header.
+pub fn load_part(path: &Path) -> AssemblyResult<TriangleMesh> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    let read_error = |message: String| AssemblyError::Read {
+        path: path.to_path_buf(),
+        message,
+    };
+
+    match extension.as_str() {
+        "stl" => {
+            let bytes = fs::read(path)?;
+            let reader = StlReader::new();
+            let mesh = if bytes.starts_with(b"solid") {
+                reader
+                    .read_ascii(bytes.as_slice())
+                    .or_else(|_| reader.read_binary(bytes.as_slice()))
+            } else {
+                reader.read_binary(bytes.as_slice())
+            }
+            .map_err(|e| read_error(e.to_string()))?;
+            Ok(mesh.to_triangle_mesh(WELD_TOLERANCE))
+        }
+        "obj" => {
+            let dir = path.parent().unwrap_or(Path::new("."));
+            ObjReader::new()
+                .skip_materials()
+                .read(BufReader::new(File::open(path)?), dir)
+                .map_err(|e| read_error(e.to_string()))?
+                .to_triangle_mesh()
+                .map_err(|e| read_error(e.to_string()))
+        }
+        _ => Err(AssemblyError::UnsupportedFormat(path.to_path_buf())),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::geometry::mesh::{TriangleFace, Vertex};
+    use nalgebra::Translation3;
+
+    /// Closed cube `[0, size]³` with outward triangles
+    pub(crate) fn cube(size: f64) -> TriangleMesh {
+        let vertices = (0..8)
+            .map(|i| {
+                let bit = |b: usize| if i & b != 0 { size } else { 0.0 };
+                Vertex::new(Point3::new(bit(1), bit(2), bit(4)))
+            })
+            .collect();
+        let faces = [
+            [0, 2, 3], [0, 3, 1], [4, 5, 7], [4, 7, 6],
+            [0, 1, 5], [0, 5, 4], [2, 6, 7], [2, 7, 3],
+            [0, 4, 6], [0, 6, 2], [1, 3, 7], [1, 7, 5],
+        ]
+        .iter()
+        .map(|&[a, b, c]| TriangleFace::new(a, b, c))
+        .collect();
+        TriangleMesh::from_data(vertices, faces)
+    }
+
+    #[test]
+    fn test_placed_bounds() {
+        let component = Component::new("Cube", "cube.stl")
+            .with_geometry(cube(2.0))
+            .with_placement(Isometry3::from_parts(
+                Translation3::new(10.0, 0.0, 0.0),
+                nalgebra::UnitQuaternion::from_axis_angle(&Vector3::z_axis(), std::f64::consts::FRAC_PI_2),
+            ));
+        let (min, max) = component.bounds().unwrap();
+        assert!((min - Point3::new(8.0, 0.0, 0.0)).norm() < 1e-9);
+        assert!((max - Point3::new(10.0, 2.0, 2.0)).norm() < 1e-9);
+        assert_eq!(component.triangles().unwrap().len(), 12);
+        assert!(Component::new("Empty", "x.stl").bounds().is_none());
+    }
+
+    #[test]
+    fn test_load_part_formats() {
+        let dir = std::env::temp_dir().join(format!("caddy-parts-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let obj = dir.join("wedge.obj");
+        fs::write(&obj, "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nf 1 3 2\nf 1 2 4\nf 1 4 3\nf 2 3 4\n")
+            .unwrap();
+        let mesh = load_part(&obj).unwrap();
+        assert_eq!((mesh.vertices.len(), mesh.faces.len()), (4, 4));
+
+        let step = dir.join("part.step");
+        fs::write(&step, "ISO-10303-21;").unwrap();
+        assert!(matches!(load_part(&step), Err(AssemblyError::UnsupportedFormat(_))));
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,172 @@
+//! Interference detection between placed components
+//!
+//! Components whose bounds overlap are sampled on a grid over the shared
+//! box; sample points inside both solids (by ray parity) measure the
+//! interfering volume. Faces that only touch share no volume and are not
+//! reported.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Assembly, AssemblyError, AssemblyResult};
+use crate::core::{Point3, Vector3, EPSILON};
+
+/// Ray direction for inside tests, skewed off the axes so rays rarely graze
+/// the edges of axis-aligned parts
+const RAY: [f64; 3] = [0.8727, 0.4014, 0.2781];
+
+/// Interference check settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct InterferenceOptions {
+    /// Samples along the longest side of each overlap box
+    pub resolution: usize,
+    /// Overlaps thinner than this in any direction count as touching
+    pub tolerance: f64,
+}
+
+impl Default for InterferenceOptions {
+    fn default() -> Self {
+        Self {
+            resolution: 24,
+            tolerance: 1e-6,
+        }
+    }
+}
+
+/// Two components whose solids overlap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interference {
+    /// Earlier component in the assembly
+    pub first: Uuid,
+    /// Later component in the assembly
+    pub second: Uuid,
+    /// Estimated shared volume
+    pub volume: f64,
+    /// Bounds of the interfering samples, minimum and maximum corner
+    pub bounds: (Point3, Point3),
+}
+
+/// Find every pair of components whose solids overlap
+///
+/// Part meshes must be closed. Overlaps thinner than one sample cell can be
+/// missed; raise [`InterferenceOptions::resolution`] for thin features.
+pub fn detect_interference(
+    assembly: &Assembly,
+    options: &InterferenceOptions,
+) -> AssemblyResult<Vec<Interference>> {
+    let mut solids = Vec::with_capacity(assembly.components.len());
+    for component in &assembly.components {
+        let (Some(triangles), Some(bounds)) = (component.triangles(), component.bounds()) else {
+            return Err(AssemblyError::NoGeometry(component.name.clone()));
+        };
+        solids.push((component.id, triangles, bounds));
+    }
+
+    let resolution = options.resolution.max(1);
+    let mut found = Vec::new();
+    for (i, (first, a, a_bounds)) in solids.iter().enumerate() {
+        for (second, b, b_bounds) in &solids[i + 1..] {
+            let min = a_bounds.0.sup(&b_bounds.0);
+            let max = a_bounds.1.inf(&b_bounds.1);
+            let size = max - min;
+            if size.min() <= options.tolerance {
+                continue;
+            }
+
+            let cell = size.max() / resolution as f64;
+            let counts = size.map(|s| ((s / cell).round() as usize).max(1));
+            let step = size.component_div(&counts.cast::<f64>());
+            let mut inside = 0;
+            let mut hit: Option<(Point3, Point3)> = None;
+            for x in 0..counts.x {
+                for y in 0..counts.y {
+                    for z in 0..counts.z {
+                        let offset = Vector3::new(x as f64 + 0.5, y as f64 + 0.5, z as f64 + 0.5);
+                        let p = min + offset.component_mul(&step);
+                        if contains(a, &p) && contains(b, &p) {
+                            inside += 1;
+                            hit = Some(match hit {
+                                Some((lo, hi)) => (lo.inf(&p), hi.sup(&p)),
+                                None => (p, p),
+                            });
+                        }
+                    }
+                }
+            }
+            if let Some((lo, hi)) = hit {
+                let half = step / 2.0;
+                found.push(Interference {
+                    first: *first,
+                    second: *second,
+                    volume: inside as f64 * step.x * step.y * step.z,
+                    bounds: (lo - half, hi + half),
+                });
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Whether a point is inside a closed triangle mesh, by counting crossings
+/// of a ray (Möller-Trumbore)
+fn contains(triangles: &[[Point3; 3]], point: &Point3) -> bool {
+    let ray = Vector3::from(RAY);
+    let crossings = triangles
+        .iter()
+        .filter(|[a, b, c]| {
+            let (e1, e2) = (b - a, c - a);
+            let p = ray.cross(&e2);
+            let det = e1.dot(&p);
+            if det.abs() < EPSILON {
+                return false;
+            }
+            let s = point - a;
+            let u = s.dot(&p) / det;
+            let q = s.cross(&e1);
+            let v = ray.dot(&q) / det;
+            u >= 0.0 && v >= 0.0 && u + v <= 1.0 && e2.dot(&q) / det > 0.0
+        })
+        .count();
+    crossings % 2 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembly::component::tests::cube;
+    use crate::assembly::Component;
+    use nalgebra::{Isometry3, Translation3};
+
+    fn cube_at(name: &str, x: f64, y: f64, z: f64) -> Component {
+        Component::new(name, "cube.stl")
+            .with_geometry(cube(2.0))
+            .with_placement(Isometry3::from_parts(Translation3::new(x, y, z), Default::default()))
+    }
+
+    #[test]
+    fn test_overlap_volume() {
+        let mut assembly = Assembly::new("Clash");
+        let a = assembly.insert(cube_at("A", 0.0, 0.0, 0.0));
+        let b = assembly.insert(cube_at("B", 1.0, 1.0, 1.0));
+        assembly.insert(cube_at("Touching", -2.0, 0.0, 0.0));
+        assembly.insert(cube_at("Apart", 10.0, 0.0, 0.0));
+
+        let found = assembly.interferences(&InterferenceOptions::default()).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].first, found[0].second), (a, b));
+        assert!((found[0].volume - 1.0).abs() < 1e-9);
+        assert!((found[0].bounds.0 - Point3::new(1.0, 1.0, 1.0)).norm() < 1e-9);
+        assert!((found[0].bounds.1 - Point3::new(2.0, 2.0, 2.0)).norm() < 1e-9);
+    }
+
+    #[test]
+    fn test_needs_geometry() {
+        let mut assembly = Assembly::new("Unloaded");
+        assembly.insert(cube_at("A", 0.0, 0.0, 0.0));
+        assembly.insert(Component::new("B", "b.stl"));
+        assert!(matches!(
+            detect_interference(&assembly, &InterferenceOptions::default()),
+            Err(AssemblyError::NoGeometry(name)) if name == "B"
+        ));
+    }
+}
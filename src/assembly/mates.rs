@@ -0,0 +1,349 @@
+//! Mates: relations between features of two components
+//!
+//! Features are given in part coordinates and carried along with their
+//! component's placement. Each mate turns into residuals that are zero when
+//! it holds; [`MateSolver`](super::MateSolver) drives them to zero.
+
+use nalgebra::Isometry3;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{AssemblyError, AssemblyResult};
+use crate::core::{Point3, Vector3, EPSILON};
+
+/// A point, axis or plane of a component, in part coordinates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MateFeature {
+    /// A vertex, sphere centre or other point
+    Point(Point3),
+    /// A line, such as the axis of a hole or shaft
+    Axis {
+        /// Point on the axis
+        origin: Point3,
+        /// Axis direction
+        direction: Vector3,
+    },
+    /// A planar face
+    Plane {
+        /// Point on the plane
+        origin: Point3,
+        /// Outward face normal
+        normal: Vector3,
+    },
+}
+
+impl MateFeature {
+    /// Point feature
+    pub fn point(point: Point3) -> Self {
+        MateFeature::Point(point)
+    }
+
+    /// Axis feature
+    pub fn axis(origin: Point3, direction: Vector3) -> Self {
+        MateFeature::Axis { origin, direction }
+    }
+
+    /// Plane feature
+    pub fn plane(origin: Point3, normal: Vector3) -> Self {
+        MateFeature::Plane { origin, normal }
+    }
+
+    /// Display name of the feature kind
+    pub fn name(&self) -> &'static str {
+        match self {
+            MateFeature::Point(_) => "point",
+            MateFeature::Axis { .. } => "axis",
+            MateFeature::Plane { .. } => "plane",
+        }
+    }
+
+    /// Feature moved by a placement, with its direction normalized
+    fn placed(&self, placement: &Isometry3<f64>) -> Self {
+        match self {
+            MateFeature::Point(p) => MateFeature::Point(placement * p),
+            MateFeature::Axis { origin, direction } => MateFeature::Axis {
+                origin: placement * origin,
+                direction: (placement * direction).normalize(),
+            },
+            MateFeature::Plane { origin, normal } => MateFeature::Plane {
+                origin: placement * origin,
+                normal: (placement * normal).normalize(),
+            },
+        }
+    }
+
+    /// Point, axis, plane: the order mates expect their features in
+    fn rank(&self) -> u8 {
+        match self {
+            MateFeature::Point(_) => 0,
+            MateFeature::Axis { .. } => 1,
+            MateFeature::Plane { .. } => 2,
+        }
+    }
+
+    fn direction(&self) -> Option<Vector3> {
+        match self {
+            MateFeature::Point(_) => None,
+            MateFeature::Axis { direction, .. } => Some(*direction),
+            MateFeature::Plane { normal, .. } => Some(*normal),
+        }
+    }
+}
+
+/// A feature on a particular component
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MateReference {
+    /// Component carrying the feature
+    pub component: Uuid,
+    /// Feature in the component's part coordinates
+    pub feature: MateFeature,
+}
+
+/// What a mate enforces
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MateKind {
+    /// Points meet, a point lies on an axis or plane, axes coincide, an
+    /// axis lies in a plane, or planes touch face to face
+    Coincident,
+    /// A point or axis lies on the other component's axis
+    Concentric,
+    /// Gap between points, a point and an axis or plane, parallel axes, an
+    /// axis and a parallel plane, or facing planes
+    Distance(f64),
+    /// Angle between axis directions or plane normals, in radians
+    Angle(f64),
+}
+
+impl MateKind {
+    /// Display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            MateKind::Coincident => "Coincident",
+            MateKind::Concentric => "Concentric",
+            MateKind::Distance(_) => "Distance",
+            MateKind::Angle(_) => "Angle",
+        }
+    }
+}
+
+/// A relation between features of two components
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mate {
+    /// Unique mate identifier
+    pub id: Uuid,
+    /// What the mate enforces
+    pub kind: MateKind,
+    /// Feature on the first component
+    pub first: MateReference,
+    /// Feature on the second component
+    pub second: MateReference,
+    /// Plane normals point the same way instead of facing each other
+    pub aligned: bool,
+    /// Disabled mates are kept but not solved
+    pub enabled: bool,
+}
+
+impl Mate {
+    /// Mate between a feature of each of two components
+    pub fn new(kind: MateKind, first: (Uuid, MateFeature), second: (Uuid, MateFeature)) -> Self {
+        let reference = |(component, feature)| MateReference { component, feature };
+        Self {
+            id: Uuid::new_v4(),
+            kind,
+            first: reference(first),
+            second: reference(second),
+            aligned: false,
+            enabled: true,
+        }
+    }
+
+    /// Coincident mate
+    pub fn coincident(first: (Uuid, MateFeature), second: (Uuid, MateFeature)) -> Self {
+        Self::new(MateKind::Coincident, first, second)
+    }
+
+    /// Concentric mate
+    pub fn concentric(first: (Uuid, MateFeature), second: (Uuid, MateFeature)) -> Self {
+        Self::new(MateKind::Concentric, first, second)
+    }
+
+    /// Distance mate
+    pub fn distance(first: (Uuid, MateFeature), second: (Uuid, MateFeature), distance: f64) -> Self {
+        Self::new(MateKind::Distance(distance), first, second)
+    }
+
+    /// Angle mate, in radians
+    pub fn angle(first: (Uuid, MateFeature), second: (Uuid, MateFeature), angle: f64) -> Self {
+        Self::new(MateKind::Angle(angle), first, second)
+    }
+
+    /// Make plane normals point the same way
+    pub fn aligned(mut self) -> Self {
+        self.aligned = true;
+        self
+    }
+
+    /// Whether the mate references a component
+    pub fn involves(&self, component: Uuid) -> bool {
+        self.first.component == component || self.second.component == component
+    }
+
+    /// Check the mate's values and that its kind applies to its features
+    pub fn validate(&self) -> AssemblyResult<()> {
+        if self.first.component == self.second.component {
+            return Err(AssemblyError::SelfMate(self.id));
+        }
+        for feature in [&self.first.feature, &self.second.feature] {
+            if feature.direction().is_some_and(|d| d.norm() < EPSILON) {
+                return Err(AssemblyError::InvalidMate(format!(
+                    "{} has no direction",
+                    feature.name()
+                )));
+            }
+        }
+        match self.kind {
+            MateKind::Distance(d) if !d.is_finite() || d < 0.0 => {
+                return Err(AssemblyError::InvalidMate(format!("distance {}", d)));
+            }
+            MateKind::Angle(a) if !(0.0..=std::f64::consts::PI).contains(&a) => {
+                return Err(AssemblyError::InvalidMate(format!("angle {}", a)));
+            }
+            _ => {}
+        }
+
+        let (a, b) = ordered(&self.first.feature, &self.second.feature);
+        use MateFeature::*;
+        let compatible = match self.kind {
+            MateKind::Coincident | MateKind::Distance(_) => true,
+            MateKind::Concentric => matches!((a, b), (Point(_) | Axis { .. }, Axis { .. })),
+            MateKind::Angle(_) => a.direction().is_some(),
+        };
+        if compatible {
+            Ok(())
+        } else {
+            Err(AssemblyError::IncompatibleFeatures {
+                kind: self.kind.name(),
+                first: self.first.feature.name(),
+                second: self.second.feature.name(),
+            })
+        }
+    }
+
+    /// Residuals with the components at these placements; all zero when
+    /// the mate holds
+    ///
+    /// Features the mate kind does not apply to give no residuals; see
+    /// [`validate`](Self::validate).
+    pub fn residuals(&self, first: &Isometry3<f64>, second: &Isometry3<f64>) -> Vec<f64> {
+        let a = self.first.feature.placed(first);
+        let b = self.second.feature.placed(second);
+        let (a, b) = ordered(&a, &b);
+        let offset = match self.kind {
+            MateKind::Coincident | MateKind::Concentric => 0.0,
+            MateKind::Distance(d) => d,
+            MateKind::Angle(angle) => {
+                return match (a.direction(), b.direction()) {
+                    (Some(u), Some(v)) => vec![u.cross(&v).norm().atan2(u.dot(&v)) - angle],
+                    _ => Vec::new(),
+                };
+            }
+        };
+        let touching = offset == 0.0;
+
+        use MateFeature::*;
+        match (a, b) {
+            (Point(p), Point(q)) if touching => (q - p).iter().copied().collect(),
+            (Point(p), Point(q)) => vec![(q - p).norm() - offset],
+            (Point(p), Axis { origin, direction }) if touching => {
+                (p - origin).cross(direction).iter().copied().collect()
+            }
+            (Point(p), Axis { origin, direction }) => {
+                vec![(p - origin).cross(direction).norm() - offset]
+            }
+            (Point(p), Plane { origin, normal }) => vec![(p - origin).dot(normal) - offset],
+            (Axis { origin: o1, direction: d1 }, Axis { origin: o2, direction: d2 }) => {
+                let mut r: Vec<f64> = d1.cross(d2).iter().copied().collect();
+                let apart = (o2 - o1).cross(d1);
+                if touching {
+                    r.extend(apart.iter());
+                } else {
+                    r.push(apart.norm() - offset);
+                }
+                r
+            }
+            (Axis { origin: o1, direction }, Plane { origin: o2, normal }) => {
+                vec![direction.dot(normal), (o1 - o2).dot(normal) - offset]
+            }
+            (Plane { origin: o1, normal: n1 }, Plane { origin: o2, normal: n2 }) => {
+                let facing = if self.aligned { n1 - n2 } else { n1 + n2 };
+                let mut r: Vec<f64> = facing.iter().copied().collect();
+                r.push((o2 - o1).dot(n1) - offset);
+                r
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Features in point, axis, plane order
+fn ordered<'a>(a: &'a MateFeature, b: &'a MateFeature) -> (&'a MateFeature, &'a MateFeature) {
+    if a.rank() <= b.rank() {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Translation3;
+
+    #[test]
+    fn test_residuals() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let top = MateFeature::plane(Point3::new(0.0, 0.0, 10.0), Vector3::z());
+        let bottom = MateFeature::plane(Point3::origin(), -Vector3::z());
+        let base = Isometry3::identity();
+        let lifted = Isometry3::from_parts(Translation3::new(3.0, 4.0, 12.0), Default::default());
+
+        let flush = Mate::coincident((a, top.clone()), (b, bottom.clone()));
+        assert_eq!(flush.residuals(&base, &lifted), vec![0.0, 0.0, 0.0, 2.0]);
+        let gap = Mate::distance((a, top), (b, bottom), 2.0);
+        assert!(gap.residuals(&base, &lifted).iter().all(|r| r.abs() < 1e-12));
+
+        // Order of features does not matter
+        let corner = MateFeature::point(Point3::origin());
+        let hole = MateFeature::axis(Point3::origin(), Vector3::z());
+        let on_axis = Mate::concentric((b, corner), (a, hole));
+        assert_eq!(on_axis.residuals(&lifted, &base), vec![4.0, -3.0, 0.0]);
+
+        let tilted = Mate::angle(
+            (a, MateFeature::axis(Point3::origin(), Vector3::x())),
+            (b, MateFeature::axis(Point3::origin(), Vector3::new(1.0, 1.0, 0.0))),
+            std::f64::consts::FRAC_PI_4,
+        );
+        assert!(tilted.residuals(&base, &lifted)[0].abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_validate() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let point = MateFeature::point(Point3::origin());
+        let plane = MateFeature::plane(Point3::origin(), Vector3::z());
+
+        assert!(Mate::coincident((a, point.clone()), (b, plane.clone())).validate().is_ok());
+        assert!(matches!(
+            Mate::concentric((a, point.clone()), (b, plane.clone())).validate(),
+            Err(AssemblyError::IncompatibleFeatures { kind: "Concentric", first: "point", .. })
+        ));
+        assert!(Mate::angle((a, point.clone()), (b, plane.clone()), 1.0).validate().is_err());
+        assert!(Mate::distance((a, point.clone()), (b, plane), -1.0).validate().is_err());
+        assert!(matches!(
+            Mate::coincident((a, point.clone()), (a, point)).validate(),
+            Err(AssemblyError::SelfMate(_))
+        ));
+        let flat = MateFeature::axis(Point3::origin(), Vector3::zeros());
+        assert!(Mate::coincident((a, flat.clone()), (b, flat)).validate().is_err());
+    }
+}
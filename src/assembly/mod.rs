@@ -0,0 +1,356 @@
+//! # CADDY Assemblies
+//!
+//! Assembly documents placing parts from external files:
+//!
+//! - **Components**: instances of STL or OBJ part files, stored by path like
+//!   xrefs and found next to the assembly or on search paths; instances of
+//!   the same file share one loaded mesh
+//! - **Mates**: coincident, concentric, distance and angle relations
+//!   between points, axes and planes of two components
+//! - **Solving**: [`MateSolver`] moves the free components until the mates
+//!   hold, reporting a [`SolverStatus`] like the sketch constraint solver
+//! - **Interference**: [`detect_interference`] finds components whose
+//!   solids overlap and estimates the shared volume
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use caddy::assembly::{Assembly, Component, Mate, MateFeature};
+//! use caddy::core::{Point3, Vector3};
+//!
+//! let mut assembly = Assembly::new("Bracket");
+//! let base = assembly.insert(Component::new("Base", "parts/base.stl").fixed());
+//! let pin = assembly.insert(Component::new("Pin", "parts/pin.stl"));
+//!
+//! let hole = MateFeature::axis(Point3::new(10.0, 0.0, 0.0), Vector3::z());
+//! let shaft = MateFeature::axis(Point3::origin(), Vector3::z());
+//! assembly.add_mate(Mate::concentric((base, hole), (pin, shaft))).unwrap();
+//!
+//! let missing = assembly.load_components(std::path::Path::new("."), &[]).unwrap();
+//! assert!(missing.is_empty());
+//! let solution = assembly.solve(&Default::default()).unwrap();
+//! println!("{:?}, {} degrees of freedom left", solution.status, solution.free_dof);
+//! ```
+
+pub mod component;
+pub mod interference;
+pub mod mates;
+pub mod solver;
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::constraints::SolverConfig;
+use crate::geometry::mesh::TriangleMesh;
+use crate::io::transmittal::{locate, DependencyKind, Reference};
+
+pub use component::{load_part, Component, PART_EXTENSIONS};
+pub use interference::{detect_interference, Interference, InterferenceOptions};
+pub use mates::{Mate, MateFeature, MateKind, MateReference};
+pub use solver::{MateSolution, MateSolver};
+
+pub use crate::constraints::SolverStatus;
+
+/// File extension of saved assemblies
+pub const ASSEMBLY_EXTENSION: &str = "cdyasm";
+
+/// An assembly document: placed components and the mates between them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Assembly {
+    /// Unique assembly identifier
+    pub id: Uuid,
+    /// Display name
+    pub name: String,
+    components: Vec<Component>,
+    mates: Vec<Mate>,
+}
+
+impl Assembly {
+    /// Create an empty assembly
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            components: Vec::new(),
+            mates: Vec::new(),
+        }
+    }
+
+    /// Add a component, returning its id
+    pub fn insert(&mut self, component: Component) -> Uuid {
+        let id = component.id;
+        self.components.push(component);
+        id
+    }
+
+    /// Remove a component together with the mates that reference it
+    pub fn remove_component(&mut self, id: Uuid) -> AssemblyResult<Component> {
+        let index = self
+            .components
+            .iter()
+            .position(|c| c.id == id)
+            .ok_or(AssemblyError::UnknownComponent(id))?;
+        self.mates.retain(|m| !m.involves(id));
+        Ok(self.components.remove(index))
+    }
+
+    /// Component by id
+    pub fn component(&self, id: Uuid) -> Option<&Component> {
+        self.components.iter().find(|c| c.id == id)
+    }
+
+    /// Mutable component by id
+    pub fn component_mut(&mut self, id: Uuid) -> Option<&mut Component> {
+        self.components.iter_mut().find(|c| c.id == id)
+    }
+
+    /// All components, in insertion order
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    /// Add a mate after checking its components and features
+    pub fn add_mate(&mut self, mate: Mate) -> AssemblyResult<Uuid> {
+        for reference in [&mate.first, &mate.second] {
+            if self.component(reference.component).is_none() {
+                return Err(AssemblyError::UnknownComponent(reference.component));
+            }
+        }
+        mate.validate()?;
+        let id = mate.id;
+        self.mates.push(mate);
+        Ok(id)
+    }
+
+    /// Remove a mate
+    pub fn remove_mate(&mut self, id: Uuid) -> AssemblyResult<Mate> {
+        let index = self
+            .mates
+            .iter()
+            .position(|m| m.id == id)
+            .ok_or(AssemblyError::UnknownMate(id))?;
+        Ok(self.mates.remove(index))
+    }
+
+    /// Mutable mate by id, e.g. to change a distance
+    pub fn mate_mut(&mut self, id: Uuid) -> Option<&mut Mate> {
+        self.mates.iter_mut().find(|m| m.id == id)
+    }
+
+    /// All mates
+    pub fn mates(&self) -> &[Mate] {
+        &self.mates
+    }
+
+    /// Part files the components refer to, as xref references without
+    /// duplicates
+    pub fn references(&self) -> Vec<Reference> {
+        let mut seen = HashSet::new();
+        self.components
+            .iter()
+            .filter(|c| seen.insert(c.source.as_str()))
+            .map(|c| Reference {
+                kind: DependencyKind::Xref,
+                value: c.source.clone(),
+            })
+            .collect()
+    }
+
+    /// Load the geometry of every component
+    ///
+    /// Part paths are resolved like xrefs: relative to `base_dir`, then in
+    /// each search path, then by bare file name. Each file is read once and
+    /// shared by every instance, whichever path it was found through.
+    /// Returns the sources that were not found; those components keep
+    /// whatever geometry they had.
+    pub fn load_components(
+        &mut self,
+        base_dir: &Path,
+        search_paths: &[PathBuf],
+    ) -> AssemblyResult<Vec<String>> {
+        let mut dirs = vec![base_dir.to_path_buf()];
+        dirs.extend(search_paths.iter().cloned());
+
+        let mut loaded: HashMap<PathBuf, Arc<TriangleMesh>> = HashMap::new();
+        let mut missing = Vec::new();
+        for component in &mut self.components {
+            let Some(path) = locate(&component.source, &dirs) else {
+                if !missing.contains(&component.source) {
+                    missing.push(component.source.clone());
+                }
+                continue;
+            };
+            let mesh = match loaded.get(&path) {
+                Some(mesh) => Arc::clone(mesh),
+                None => {
+                    let mesh = Arc::new(load_part(&path)?);
+                    loaded.insert(path, Arc::clone(&mesh));
+                    mesh
+                }
+            };
+            component.geometry = Some(mesh);
+        }
+        Ok(missing)
+    }
+
+    /// Move the free components until the mates hold
+    pub fn solve(&mut self, config: &SolverConfig) -> AssemblyResult<MateSolution> {
+        MateSolver::new(config.clone()).solve(self)
+    }
+
+    /// Pairs of components whose solids overlap
+    pub fn interferences(&self, options: &InterferenceOptions) -> AssemblyResult<Vec<Interference>> {
+        detect_interference(self, options)
+    }
+
+    /// Write the assembly as JSON; component geometry stays in the part
+    /// files
+    pub fn save(&self, path: impl AsRef<Path>) -> AssemblyResult<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Read a saved assembly and load its components relative to the
+    /// assembly file
+    ///
+    /// Returns the assembly and the part files that were not found.
+    pub fn open(
+        path: impl AsRef<Path>,
+        search_paths: &[PathBuf],
+    ) -> AssemblyResult<(Self, Vec<String>)> {
+        let path = path.as_ref();
+        let mut assembly: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let missing = assembly.load_components(dir, search_paths)?;
+        Ok((assembly, missing))
+    }
+}
+
+/// Assembly errors
+#[derive(Debug, Error)]
+pub enum AssemblyError {
+    /// No component with this id
+    #[error("No component {0}")]
+    UnknownComponent(Uuid),
+
+    /// No mate with this id
+    #[error("No mate {0}")]
+    UnknownMate(Uuid),
+
+    /// Both sides of a mate are on the same component
+    #[error("Mate {0} joins a component to itself")]
+    SelfMate(Uuid),
+
+    /// The mate kind does not apply to the chosen features
+    #[error("{kind} mate cannot join {first} and {second}")]
+    IncompatibleFeatures {
+        /// Mate kind
+        kind: &'static str,
+        /// Feature kind on the first component
+        first: &'static str,
+        /// Feature kind on the second component
+        second: &'static str,
+    },
+
+    /// Distance or angle out of range, or a zero-length direction
+    #[error("Invalid mate: {0}")]
+    InvalidMate(String),
+
+    /// Part file type is not a mesh format
+    #[error("Unsupported part format: {0}")]
+    UnsupportedFormat(PathBuf),
+
+    /// Part file could not be parsed
+    #[error("Failed to read {path}: {message}")]
+    Read {
+        /// Part file
+        path: PathBuf,
+        /// Reader error
+        message: String,
+    },
+
+    /// The component's part file has not been loaded
+    #[error("Component {0} has no geometry loaded")]
+    NoGeometry(String),
+
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Malformed assembly file
+    #[error("Invalid assembly file: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Result type for assembly operations
+pub type AssemblyResult<T> = Result<T, AssemblyError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Point3, Vector3};
+
+    #[test]
+    fn test_references_and_removal() {
+        let mut assembly = Assembly::new("Frame");
+        let a = assembly.insert(Component::new("A", "parts/plate.stl"));
+        let b = assembly.insert(Component::new("B", "parts/plate.stl"));
+        let c = assembly.insert(Component::new("C", "bolt.obj"));
+        let plane = MateFeature::plane(Point3::origin(), Vector3::z());
+        assembly.add_mate(Mate::coincident((a, plane.clone()), (b, plane.clone()))).unwrap();
+        let bc = assembly.add_mate(Mate::distance((b, plane.clone()), (c, plane), 5.0)).unwrap();
+
+        let refs = assembly.references();
+        assert_eq!(refs.len(), 2);
+        assert!(refs.iter().all(|r| r.kind == DependencyKind::Xref));
+        assert_eq!(refs[1].value, "bolt.obj");
+
+        assembly.remove_component(a).unwrap();
+        assert_eq!(assembly.mates().len(), 1);
+        assert_eq!(assembly.mates()[0].id, bc);
+        assert!(matches!(
+            assembly.remove_component(a),
+            Err(AssemblyError::UnknownComponent(_))
+        ));
+    }
+
+    #[test]
+    fn test_save_open_resolves_search_paths() {
+        let root = std::env::temp_dir().join(format!("caddy-assembly-{}", Uuid::new_v4()));
+        let library = root.join("library");
+        fs::create_dir_all(&library).unwrap();
+        let cube = component::tests::cube(1.0);
+        let mut stl = String::from("solid cube\n");
+        for face in &cube.faces {
+            stl.push_str("facet normal 0 0 0\nouter loop\n");
+            for &v in &face.vertices {
+                let p = cube.vertices[v].position;
+                stl.push_str(&format!("vertex {} {} {}\n", p.x, p.y, p.z));
+            }
+            stl.push_str("endloop\nendfacet\n");
+        }
+        stl.push_str("endsolid cube\n");
+        fs::write(library.join("cube.stl"), stl).unwrap();
+
+        let mut assembly = Assembly::new("Stack");
+        let first = assembly.insert(Component::new("First", "C:/old/machine/cube.stl"));
+        let second = assembly.insert(Component::new("Second", "cube.stl"));
+        assembly.insert(Component::new("Lost", "missing.stl"));
+        let file = root.join(format!("stack.{}", ASSEMBLY_EXTENSION));
+        assembly.save(&file).unwrap();
+
+        let (opened, missing) = Assembly::open(&file, &[library]).unwrap();
+        assert_eq!(missing, vec!["missing.stl".to_string()]);
+        let a = opened.component(first).unwrap().geometry().unwrap();
+        let b = opened.component(second).unwrap().geometry().unwrap();
+        assert!(Arc::ptr_eq(a, b));
+        assert_eq!(a.faces.len(), 12);
+        fs::remove_dir_all(root).ok();
+    }
+}
@@ -0,0 +1,316 @@
+//! Rigid-body mate solver
+//!
+//! Each free component contributes six unknowns, a translation and a
+//! rotation vector applied about its current position. The solver runs
+//! damped least squares (Levenberg-Marquardt) on the stacked mate residuals
+//! with a finite-difference Jacobian, folding each accepted step into the
+//! placements so rotations are always linearized about the current pose.
+
+use std::collections::HashMap;
+use std::f64::consts::FRAC_PI_4;
+
+use nalgebra::{DMatrix, DVector, Isometry3, Translation3, UnitQuaternion, Vector3};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Assembly, AssemblyError, AssemblyResult, Mate};
+use crate::constraints::{SolverConfig, SolverStatus};
+
+/// Finite-difference step for the Jacobian
+const DIFFERENCE_STEP: f64 = 1e-7;
+
+/// Largest rotation taken in one step, in radians
+const MAX_ROTATION_STEP: f64 = FRAC_PI_4;
+
+/// Damping beyond which a step is considered impossible
+const MAX_DAMPING: f64 = 1e12;
+
+/// Outcome of solving an assembly
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MateSolution {
+    /// Solved, conflicting mates (`OverConstrained`) or out of iterations
+    pub status: SolverStatus,
+    /// Iterations used
+    pub iterations: usize,
+    /// Norm of all mate residuals after solving
+    pub residual: f64,
+    /// Degrees of freedom the mates leave the free components
+    pub free_dof: usize,
+    /// Enabled mates that still do not hold
+    pub unsatisfied: Vec<Uuid>,
+}
+
+/// Moves free components until their mates hold
+#[derive(Debug, Clone, Default)]
+pub struct MateSolver {
+    /// Iteration limit, tolerance, step size and minimum improvement
+    pub config: SolverConfig,
+}
+
+impl MateSolver {
+    /// Create a solver
+    pub fn new(config: SolverConfig) -> Self {
+        Self { config }
+    }
+
+    /// Solve the assembly's enabled mates, moving its free components
+    ///
+    /// Fixed components never move. When the mates cannot all hold the
+    /// closest placements found are kept and the status is
+    /// `OverConstrained`.
+    pub fn solve(&self, assembly: &mut Assembly) -> AssemblyResult<MateSolution> {
+        let index: HashMap<Uuid, usize> = assembly
+            .components
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.id, i))
+            .collect();
+        let mut mates = Vec::new();
+        for mate in assembly.mates.iter().filter(|m| m.enabled) {
+            let side = |component: Uuid| {
+                index
+                    .get(&component)
+                    .copied()
+                    .ok_or(AssemblyError::UnknownComponent(component))
+            };
+            mates.push((mate, side(mate.first.component)?, side(mate.second.component)?));
+        }
+        let free: Vec<usize> = (0..assembly.components.len())
+            .filter(|&i| !assembly.components[i].fixed)
+            .collect();
+
+        let mut system = System {
+            mates,
+            free,
+            placements: assembly.components.iter().map(|c| c.placement).collect(),
+        };
+        let solution = self.run(&mut system);
+        for (component, placement) in assembly.components.iter_mut().zip(&system.placements) {
+            component.placement = *placement;
+        }
+        Ok(solution)
+    }
+
+    fn run(&self, system: &mut System) -> MateSolution {
+        let unknowns = 6 * system.free.len();
+        let mut residuals = system.residuals(&system.placements);
+        let mut error = residuals.norm();
+        let mut damping = 1e-3;
+        let mut iterations = 0;
+        let mut status = SolverStatus::MaxIterationsReached;
+
+        while iterations < self.config.max_iterations {
+            if error < self.config.tolerance {
+                status = SolverStatus::Solved;
+                break;
+            }
+            if unknowns == 0 {
+                status = SolverStatus::OverConstrained;
+                break;
+            }
+            iterations += 1;
+
+            let jacobian = system.jacobian(&residuals);
+            let normal = jacobian.transpose() * &jacobian;
+            let gradient = jacobian.transpose() * &residuals;
+            let mut accepted = false;
+            while damping < MAX_DAMPING {
+                let mut damped = normal.clone();
+                for i in 0..unknowns {
+                    damped[(i, i)] += damping * (1.0 + normal[(i, i)]);
+                }
+                let Some(step) = damped.cholesky().map(|c| c.solve(&-&gradient)) else {
+                    damping *= 4.0;
+                    continue;
+                };
+                let trial = system.stepped(&system.placements, &self.clamped(step));
+                let trial_residuals = system.residuals(&trial);
+                let trial_error = trial_residuals.norm();
+                if trial_error < error {
+                    let improvement = error - trial_error;
+                    system.placements = trial;
+                    residuals = trial_residuals;
+                    error = trial_error;
+                    damping = (damping / 3.0).max(1e-12);
+                    accepted = improvement >= self.config.min_improvement || error < self.config.tolerance;
+                    break;
+                }
+                damping *= 4.0;
+            }
+            if !accepted && error >= self.config.tolerance {
+                // Stationary at a nonzero residual: the mates contradict
+                status = SolverStatus::OverConstrained;
+                break;
+            }
+        }
+        if error < self.config.tolerance {
+            status = SolverStatus::Solved;
+        }
+
+        let rank = if unknowns == 0 || residuals.is_empty() {
+            0
+        } else {
+            let singular = system.jacobian(&residuals).singular_values();
+            let largest = singular.max().max(1.0);
+            singular.iter().filter(|&&s| s > 1e-6 * largest).count()
+        };
+        let unsatisfied = system
+            .mates
+            .iter()
+            .filter(|(mate, a, b)| {
+                let r = mate.residuals(&system.placements[*a], &system.placements[*b]);
+                r.iter().map(|x| x * x).sum::<f64>().sqrt() >= self.config.tolerance
+            })
+            .map(|(mate, _, _)| mate.id)
+            .collect();
+
+        MateSolution {
+            status,
+            iterations,
+            residual: error,
+            free_dof: unknowns - rank,
+            unsatisfied,
+        }
+    }
+
+    /// Limit a step's translation and rotation per component
+    fn clamped(&self, mut step: DVector<f64>) -> DVector<f64> {
+        for k in 0..step.len() / 6 {
+            for (offset, limit) in [(0, self.config.max_step_size), (3, MAX_ROTATION_STEP)] {
+                let mut part = step.fixed_rows_mut::<3>(6 * k + offset);
+                let length = part.norm();
+                if length > limit {
+                    part *= limit / length;
+                }
+            }
+        }
+        step
+    }
+}
+
+/// The mates being solved and the current placements
+struct System<'a> {
+    /// Enabled mates with the indices of their two components
+    mates: Vec<(&'a Mate, usize, usize)>,
+    /// Indices of the components the solver may move
+    free: Vec<usize>,
+    placements: Vec<Isometry3<f64>>,
+}
+
+impl System<'_> {
+    fn residuals(&self, placements: &[Isometry3<f64>]) -> DVector<f64> {
+        let values: Vec<f64> = self
+            .mates
+            .iter()
+            .flat_map(|(mate, a, b)| mate.residuals(&placements[*a], &placements[*b]))
+            .collect();
+        DVector::from_vec(values)
+    }
+
+    /// Placements after moving each free component by its six step values
+    fn stepped(&self, placements: &[Isometry3<f64>], step: &DVector<f64>) -> Vec<Isometry3<f64>> {
+        let mut moved = placements.to_vec();
+        for (k, &i) in self.free.iter().enumerate() {
+            let translation = Vector3::new(step[6 * k], step[6 * k + 1], step[6 * k + 2]);
+            let rotation = Vector3::new(step[6 * k + 3], step[6 * k + 4], step[6 * k + 5]);
+            let current = placements[i];
+            moved[i] = Isometry3::from_parts(
+                Translation3::from(current.translation.vector + translation),
+                UnitQuaternion::from_scaled_axis(rotation) * current.rotation,
+            );
+        }
+        moved
+    }
+
+    fn jacobian(&self, residuals: &DVector<f64>) -> DMatrix<f64> {
+        let unknowns = 6 * self.free.len();
+        let mut jacobian = DMatrix::zeros(residuals.len(), unknowns);
+        let mut step = DVector::zeros(unknowns);
+        for j in 0..unknowns {
+            step[j] = DIFFERENCE_STEP;
+            let moved = self.residuals(&self.stepped(&self.placements, &step));
+            jacobian.set_column(j, &((moved - residuals) / DIFFERENCE_STEP));
+            step[j] = 0.0;
+        }
+        jacobian
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembly::{Component, MateFeature};
+    use crate::core::Point3;
+    use std::f64::consts::FRAC_PI_3;
+
+    fn placed(x: f64, y: f64, z: f64, tilt: f64) -> Isometry3<f64> {
+        Isometry3::new(Vector3::new(x, y, z), Vector3::new(tilt, 0.5 * tilt, 0.0))
+    }
+
+    #[test]
+    fn test_pin_in_hole() {
+        let mut assembly = Assembly::new("Pin");
+        let plate = assembly.insert(Component::new("Plate", "plate.stl").fixed());
+        let pin = Component::new("Pin", "pin.stl").with_placement(placed(3.0, -2.0, 14.0, 0.3));
+        let pin = assembly.insert(pin);
+        let hole = MateFeature::axis(Point3::new(5.0, 5.0, 0.0), Vector3::z());
+        let top = MateFeature::plane(Point3::new(0.0, 0.0, 10.0), Vector3::z());
+        let shaft = MateFeature::axis(Point3::new(0.5, 0.5, 0.0), Vector3::z());
+        let bottom = MateFeature::plane(Point3::new(0.5, 0.5, 0.0), -Vector3::z());
+        assembly.add_mate(Mate::concentric((plate, hole), (pin, shaft))).unwrap();
+        assembly.add_mate(Mate::coincident((plate, top), (pin, bottom))).unwrap();
+
+        let solution = assembly.solve(&SolverConfig::default()).unwrap();
+        assert_eq!(solution.status, SolverStatus::Solved);
+        assert!(solution.unsatisfied.is_empty());
+        // Only the spin about the hole axis is left
+        assert_eq!(solution.free_dof, 1);
+
+        let pin = assembly.component(pin).unwrap();
+        let foot = pin.to_assembly_point(&Point3::new(0.5, 0.5, 0.0));
+        assert!((foot - Point3::new(5.0, 5.0, 10.0)).norm() < 1e-5);
+        assert!((pin.to_assembly_vector(&Vector3::z()) - Vector3::z()).norm() < 1e-5);
+        assert_eq!(assembly.component(plate).unwrap().placement, Isometry3::identity());
+    }
+
+    #[test]
+    fn test_hinge_angle_and_distance() {
+        let mut assembly = Assembly::new("Hinge");
+        let leaf = assembly.insert(Component::new("Leaf", "leaf.stl").fixed());
+        let door = Component::new("Door", "door.stl").with_placement(placed(0.5, 1.0, -1.0, 0.4));
+        let door = assembly.insert(door);
+        let pivot = MateFeature::axis(Point3::origin(), Vector3::x());
+        let face = MateFeature::plane(Point3::origin(), Vector3::z());
+        let end = MateFeature::plane(Point3::origin(), Vector3::x());
+        assembly.add_mate(Mate::concentric((leaf, pivot.clone()), (door, pivot))).unwrap();
+        assembly.add_mate(Mate::angle((leaf, face.clone()), (door, face), FRAC_PI_3)).unwrap();
+        assembly.add_mate(Mate::distance((leaf, end.clone()), (door, end), 2.0).aligned()).unwrap();
+
+        let solution = assembly.solve(&SolverConfig::default()).unwrap();
+        assert_eq!(solution.status, SolverStatus::Solved);
+        assert_eq!(solution.free_dof, 0);
+        let door = assembly.component(door).unwrap();
+        let normal = door.to_assembly_vector(&Vector3::z());
+        assert!((normal.angle(&Vector3::z()) - FRAC_PI_3).abs() < 1e-5);
+        assert!(normal.x.abs() < 1e-5);
+        assert!((door.to_assembly_point(&Point3::origin()) - Point3::new(2.0, 0.0, 0.0)).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_conflicting_mates() {
+        let mut assembly = Assembly::new("Conflict");
+        let a = assembly.insert(Component::new("A", "a.stl").fixed());
+        let b = assembly.insert(Component::new("B", "b.stl").with_placement(placed(1.0, 0.0, 0.0, 0.0)));
+        let corner = MateFeature::point(Point3::origin());
+        assembly.add_mate(Mate::distance((a, corner.clone()), (b, corner.clone()), 2.0)).unwrap();
+        let far = assembly.add_mate(Mate::distance((a, corner.clone()), (b, corner), 5.0)).unwrap();
+
+        let solution = assembly.solve(&SolverConfig::default()).unwrap();
+        assert_eq!(solution.status, SolverStatus::OverConstrained);
+        assert!(solution.unsatisfied.contains(&far));
+        assert!(solution.residual > 1.0);
+
+        assembly.mate_mut(far).unwrap().enabled = false;
+        assert_eq!(assembly.solve(&SolverConfig::default()).unwrap().status, SolverStatus::Solved);
+    }
+}
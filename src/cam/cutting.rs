@@ -0,0 +1,1185 @@
+//! Laser and plasma cutting export
+//!
+//! Builds cut-ready paths from the closed profiles in a drawing:
+//!
+//! - Kerf compensation: outlines grow and holes shrink by half the kerf
+//! - Lead-ins and lead-outs placed on the scrap side of every profile
+//! - Holes are cut before the outline that contains them, so parts do not
+//!   drop out of the sheet early
+//! - Remaining order chosen nearest-neighbour to keep rapid travel short
+//! - Warnings for features too small to cut cleanly, open profiles, and
+//!   profiles that vanish under the kerf offset
+//!
+//! The result is written as DXF (layers `CUT` and `LEAD`) or posted as G-code.
+
+use std::f64::consts::{FRAC_PI_2, TAU};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::operations::{dedup_closing_point, make_ccw, offset_loop, signed_area};
+use super::post::{Controller, PostProcessor, Writer};
+use super::{CamError, CamResult};
+use crate::core::Point3;
+use crate::geometry::{flatten, Point2D, Polygon2D};
+use crate::io::document::{
+    Color, Document, Ellipse, Entity, GeometryType, Layer, LineType, LineWeight, Polyline, Vec3,
+    Vertex,
+};
+use crate::io::dxf::{DxfVersion, DxfWriter};
+use crate::io::import::to_curve;
+
+/// Layer holding kerf-compensated cut paths in exported DXF
+pub const CUT_LAYER: &str = "CUT";
+
+/// Layer holding lead-ins and lead-outs in exported DXF
+pub const LEAD_LAYER: &str = "LEAD";
+
+/// Thermal cutting process
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CuttingProcess {
+    /// Laser with beam power sent as the S word
+    Laser {
+        /// Beam power (S value)
+        power: f64,
+    },
+    /// Plasma torch with height control through Z
+    Plasma {
+        /// Torch height while piercing
+        pierce_height: f64,
+        /// Torch height while cutting
+        cut_height: f64,
+        /// Travel height between cuts
+        safe_height: f64,
+    },
+}
+
+/// Lead-in / lead-out shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LeadStyle {
+    /// Pierce directly on the profile
+    None,
+    /// Straight lead along the corner bisector
+    Line,
+    /// Quarter arc tangent to the profile
+    Arc,
+}
+
+/// Settings for a cutting job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CuttingSettings {
+    /// Cutting process
+    pub process: CuttingProcess,
+    /// Width of material removed by the beam or arc
+    pub kerf_width: f64,
+    /// Lead shape
+    pub lead_style: LeadStyle,
+    /// Lead-in length (radius for arc leads)
+    pub lead_in_length: f64,
+    /// Lead-out length (radius for arc leads)
+    pub lead_out_length: f64,
+    /// Cutting feed rate (units/min)
+    pub feed_rate: f64,
+    /// Dwell after piercing in seconds
+    pub pierce_delay: f64,
+    /// Profiles smaller than this in either direction are reported
+    pub min_feature_size: f64,
+    /// Maximum deviation when flattening arcs, circles and splines
+    pub chord_tolerance: f64,
+    /// Endpoint distance at which separate lines and arcs are joined
+    pub join_tolerance: f64,
+}
+
+impl CuttingSettings {
+    /// Typical settings for fiber laser cutting of thin sheet
+    pub fn laser() -> Self {
+        Self {
+            process: CuttingProcess::Laser { power: 1000.0 },
+            kerf_width: 0.15,
+            lead_style: LeadStyle::Arc,
+            lead_in_length: 1.0,
+            lead_out_length: 0.5,
+            feed_rate: 3000.0,
+            pierce_delay: 0.2,
+            min_feature_size: 0.5,
+            chord_tolerance: 0.01,
+            join_tolerance: 0.01,
+        }
+    }
+
+    /// Typical settings for plasma cutting of plate
+    pub fn plasma() -> Self {
+        Self {
+            process: CuttingProcess::Plasma {
+                pierce_height: 3.8,
+                cut_height: 1.5,
+                safe_height: 10.0,
+            },
+            kerf_width: 1.5,
+            lead_style: LeadStyle::Line,
+            lead_in_length: 5.0,
+            lead_out_length: 2.0,
+            feed_rate: 2000.0,
+            pierce_delay: 0.5,
+            min_feature_size: 6.0,
+            chord_tolerance: 0.05,
+            join_tolerance: 0.05,
+        }
+    }
+
+    /// Check that the settings are usable
+    pub fn validate(&self) -> CamResult<()> {
+        if self.kerf_width.is_nan() || self.kerf_width < 0.0 {
+            return Err(CamError::InvalidParameters(
+                "kerf width cannot be negative".into(),
+            ));
+        }
+        if self.feed_rate.is_nan() || self.feed_rate <= 0.0 {
+            return Err(CamError::InvalidParameters(
+                "feed rate must be positive".into(),
+            ));
+        }
+        if self.chord_tolerance.is_nan() || self.chord_tolerance <= 0.0 {
+            return Err(CamError::InvalidParameters(
+                "chord tolerance must be positive".into(),
+            ));
+        }
+        if self.lead_in_length < 0.0 || self.lead_out_length < 0.0 || self.pierce_delay < 0.0 {
+            return Err(CamError::InvalidParameters(
+                "lead lengths and pierce delay cannot be negative".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// What a cut produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContourRole {
+    /// Outer boundary of a part (kerf outside)
+    Outline,
+    /// Hole inside a part (kerf inside)
+    Hole,
+    /// Open profile cut on the line
+    Open,
+}
+
+impl fmt::Display for ContourRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContourRole::Outline => write!(f, "outline"),
+            ContourRole::Hole => write!(f, "hole"),
+            ContourRole::Open => write!(f, "open"),
+        }
+    }
+}
+
+/// Problems found while preparing a cutting job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FabricationWarning {
+    /// Profile is smaller than the minimum feature size
+    SmallFeature {
+        /// First entity of the profile
+        source: Option<Uuid>,
+        /// Smallest extent of the profile
+        size: f64,
+    },
+    /// Profile disappears when compensated for the kerf; not cut
+    KerfCollapse {
+        /// First entity of the profile
+        source: Option<Uuid>,
+    },
+    /// Profile does not close; cut on the line without compensation
+    OpenContour {
+        /// First entity of the profile
+        source: Option<Uuid>,
+    },
+    /// Lead-in had to be shortened to stay in the scrap
+    LeadShortened {
+        /// First entity of the profile
+        source: Option<Uuid>,
+        /// Lead length used (0 when piercing on the profile)
+        length: f64,
+    },
+    /// Entity type that cannot be cut
+    UnsupportedEntity {
+        /// Entity id
+        source: Uuid,
+        /// Entity type name
+        kind: String,
+    },
+}
+
+impl fmt::Display for FabricationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FabricationWarning::SmallFeature { size, .. } => {
+                write!(
+                    f,
+                    "feature of {:.3} is below the minimum cuttable size",
+                    size
+                )
+            }
+            FabricationWarning::KerfCollapse { .. } => {
+                write!(
+                    f,
+                    "profile collapses under kerf compensation and was skipped"
+                )
+            }
+            FabricationWarning::OpenContour { .. } => {
+                write!(
+                    f,
+                    "open profile is cut on the line without kerf compensation"
+                )
+            }
+            FabricationWarning::LeadShortened { length, .. } => {
+                write!(f, "lead-in shortened to {:.3} to stay in the scrap", length)
+            }
+            FabricationWarning::UnsupportedEntity { kind, .. } => {
+                write!(f, "{} entities cannot be cut", kind)
+            }
+        }
+    }
+}
+
+/// One pierce-to-pierce cut
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CutPath {
+    /// Entities the profile was built from
+    pub sources: Vec<Uuid>,
+    /// Outline, hole, or open profile
+    pub role: ContourRole,
+    /// Lead-in from the pierce point to the profile (empty without a lead)
+    pub lead_in: Vec<Point2D>,
+    /// Kerf-compensated profile; closed profiles end where they start
+    pub path: Vec<Point2D>,
+    /// Lead-out leaving the profile (empty without a lead)
+    pub lead_out: Vec<Point2D>,
+}
+
+impl CutPath {
+    /// Where the beam turns on
+    pub fn pierce_point(&self) -> Point2D {
+        self.lead_in.first().copied().unwrap_or(self.path[0])
+    }
+
+    /// Where the beam turns off
+    pub fn end_point(&self) -> Point2D {
+        self.lead_out
+            .last()
+            .or(self.path.last())
+            .copied()
+            .unwrap_or(self.path[0])
+    }
+
+    /// Length cut with the beam on, including leads
+    pub fn cut_length(&self) -> f64 {
+        path_length(&self.lead_in) + path_length(&self.path) + path_length(&self.lead_out)
+    }
+
+    /// All points in cutting order, without repeating the lead joints
+    fn points(&self) -> Vec<Point2D> {
+        let mut points = self.lead_in.clone();
+        let skip = usize::from(!points.is_empty());
+        points.extend(self.path.iter().skip(skip));
+        points.extend(self.lead_out.iter().skip(1));
+        points
+    }
+}
+
+/// Ordered, kerf-compensated cuts for one sheet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CuttingJob {
+    /// Settings the job was built with
+    pub settings: CuttingSettings,
+    /// Cuts in machine order
+    pub cuts: Vec<CutPath>,
+    /// Problems found while preparing the job
+    pub warnings: Vec<FabricationWarning>,
+}
+
+impl CuttingJob {
+    /// Build a job from the visible lines, arcs, circles, ellipses, and
+    /// polylines of a drawing
+    pub fn from_document(doc: &Document, settings: CuttingSettings) -> CamResult<Self> {
+        settings.validate()?;
+        let mut warnings = Vec::new();
+        let profiles = collect_profiles(doc, &settings, &mut warnings);
+        Ok(Self::build(profiles, settings, warnings))
+    }
+
+    /// Build a job from closed profiles given as point loops
+    pub fn from_profiles(profiles: &[Vec<Point2D>], settings: CuttingSettings) -> CamResult<Self> {
+        settings.validate()?;
+        let profiles = profiles
+            .iter()
+            .map(|points| Profile {
+                points: points.clone(),
+                closed: true,
+                sources: Vec::new(),
+            })
+            .collect();
+        Ok(Self::build(profiles, settings, Vec::new()))
+    }
+
+    /// Number of pierces
+    pub fn pierce_count(&self) -> usize {
+        self.cuts.len()
+    }
+
+    /// Total length cut with the beam on
+    pub fn cut_length(&self) -> f64 {
+        self.cuts.iter().map(|c| c.cut_length()).sum()
+    }
+
+    /// Rapid travel from the origin through every cut
+    pub fn travel_length(&self) -> f64 {
+        let mut current = Point2D::new(0.0, 0.0);
+        let mut total = 0.0;
+        for cut in &self.cuts {
+            total += current.distance_to(&cut.pierce_point());
+            current = cut.end_point();
+        }
+        total
+    }
+
+    /// Cut paths as a drawing with `CUT` and `LEAD` layers
+    pub fn to_document(&self) -> Document {
+        let mut doc = Document::new();
+        doc.add_layer(cut_layer(CUT_LAYER, Color::red()));
+        doc.add_layer(cut_layer(LEAD_LAYER, Color::new(255, 255, 0)));
+
+        for (i, cut) in self.cuts.iter().enumerate() {
+            let closed = cut.role != ContourRole::Open;
+            let mut path = cut.path.clone();
+            if closed {
+                path.pop();
+            }
+            let mut entity = polyline_entity(&path, closed, CUT_LAYER);
+            entity
+                .attributes
+                .insert("cut_order".to_string(), (i + 1).to_string());
+            entity
+                .attributes
+                .insert("cut_role".to_string(), cut.role.to_string());
+            doc.add_entity(entity);
+
+            for lead in [&cut.lead_in, &cut.lead_out] {
+                if lead.len() >= 2 {
+                    doc.add_entity(polyline_entity(lead, false, LEAD_LAYER));
+                }
+            }
+        }
+        doc
+    }
+
+    /// Cut paths as DXF text
+    pub fn to_dxf(&self) -> CamResult<String> {
+        let mut buffer = Vec::new();
+        DxfWriter::new(DxfVersion::R2000)
+            .write(&self.to_document(), &mut buffer)
+            .map_err(|e| CamError::Export(e.to_string()))?;
+        String::from_utf8(buffer).map_err(|e| CamError::Export(e.to_string()))
+    }
+
+    /// Post the job as G-code
+    pub fn to_gcode(&self, post: &PostProcessor) -> String {
+        let mut out = Writer::new(post);
+        post.header(&mut out);
+        let feed = self.settings.feed_rate;
+
+        // Lasers have no Z motion; NaN leaves the axis out of every move
+        let (travel_z, cut_z) = match self.settings.process {
+            CuttingProcess::Laser { .. } => (f64::NAN, f64::NAN),
+            CuttingProcess::Plasma {
+                safe_height,
+                cut_height,
+                ..
+            } => (safe_height, cut_height),
+        };
+        if !travel_z.is_nan() {
+            out.motion("G0", &Point3::new(f64::NAN, f64::NAN, travel_z), None);
+        }
+
+        for (i, cut) in self.cuts.iter().enumerate() {
+            out.comment(&format!("Cut {} {}", i + 1, cut.role));
+            let pierce = cut.pierce_point();
+            out.motion("G0", &Point3::new(pierce.x, pierce.y, travel_z), None);
+
+            match self.settings.process {
+                CuttingProcess::Laser { power } => {
+                    // Grbl's M4 scales power with speed through corners
+                    let on = if post.controller == Controller::Grbl {
+                        "M4"
+                    } else {
+                        "M03"
+                    };
+                    out.line(&format!("{} S{:.0}", on, power));
+                    self.pierce_dwell(&mut out, post);
+                }
+                CuttingProcess::Plasma { pierce_height, .. } => {
+                    out.motion("G0", &Point3::new(pierce.x, pierce.y, pierce_height), None);
+                    out.line("M03");
+                    self.pierce_dwell(&mut out, post);
+                    out.motion("G1", &Point3::new(pierce.x, pierce.y, cut_z), Some(feed));
+                }
+            }
+
+            for p in cut.points().iter().skip(1) {
+                out.motion("G1", &Point3::new(p.x, p.y, cut_z), Some(feed));
+            }
+            out.line("M05");
+            if !travel_z.is_nan() {
+                let end = cut.end_point();
+                out.motion("G0", &Point3::new(end.x, end.y, travel_z), None);
+            }
+        }
+
+        post.footer(&mut out);
+        out.finish()
+    }
+
+    fn pierce_dwell(&self, out: &mut Writer, post: &PostProcessor) {
+        if self.settings.pierce_delay > 0.0 {
+            out.line(&format!(
+                "G4 P{}",
+                post.dwell_word(self.settings.pierce_delay)
+            ));
+        }
+    }
+
+    fn build(
+        profiles: Vec<Profile>,
+        settings: CuttingSettings,
+        mut warnings: Vec<FabricationWarning>,
+    ) -> Self {
+        let mut loops: Vec<Profile> = Vec::new();
+        let mut open: Vec<Profile> = Vec::new();
+        for mut profile in profiles {
+            if profile.closed {
+                profile.points = dedup_closing_point(&profile.points, true);
+                if profile.points.len() < 3 || signed_area(&profile.points).abs() < 1e-12 {
+                    continue;
+                }
+                make_ccw(&mut profile.points);
+                loops.push(profile);
+            } else {
+                profile.points = dedup_closing_point(&profile.points, false);
+                if profile.points.len() >= 2 {
+                    warnings.push(FabricationWarning::OpenContour {
+                        source: profile.source(),
+                    });
+                    open.push(profile);
+                }
+            }
+        }
+
+        // Nesting: each loop's parent is the smallest loop containing it
+        let polygons: Vec<Polygon2D> = loops
+            .iter()
+            .map(|l| Polygon2D::new(l.points.clone()))
+            .collect();
+        let areas: Vec<f64> = polygons.iter().map(|p| p.area()).collect();
+        let parents: Vec<Option<usize>> = (0..loops.len())
+            .map(|i| {
+                (0..loops.len())
+                    .filter(|&j| j != i && areas[j] > areas[i])
+                    .filter(|&j| polygons[j].contains_point(&loops[i].points[0]))
+                    .min_by(|&a, &b| areas[a].total_cmp(&areas[b]))
+            })
+            .collect();
+        let depth = |mut i: usize| {
+            let mut d = 0;
+            while let Some(p) = parents[i] {
+                d += 1;
+                i = p;
+            }
+            d
+        };
+
+        // Compensate for the kerf; loops that vanish are skipped
+        let half_kerf = settings.kerf_width / 2.0;
+        let mut compensated: Vec<Option<(ContourRole, Vec<Point2D>)>> = Vec::new();
+        for (i, profile) in loops.iter().enumerate() {
+            let role = if depth(i) % 2 == 0 {
+                ContourRole::Outline
+            } else {
+                ContourRole::Hole
+            };
+            let size = polygons[i]
+                .bounding_box()
+                .map(|b| (b.max.x - b.min.x).min(b.max.y - b.min.y))
+                .unwrap_or(0.0);
+            if size < settings.min_feature_size {
+                warnings.push(FabricationWarning::SmallFeature {
+                    source: profile.source(),
+                    size,
+                });
+            }
+
+            let distance = if role == ContourRole::Outline {
+                half_kerf
+            } else {
+                -half_kerf
+            };
+            let offset = if half_kerf > 0.0 {
+                offset_loop(&profile.points, distance)
+            } else {
+                Some(profile.points.clone())
+            };
+            match offset {
+                Some(mut points) => {
+                    // Keep the part on the right of travel: outlines run
+                    // clockwise, holes counter-clockwise.
+                    if role == ContourRole::Outline {
+                        points.reverse();
+                    }
+                    compensated.push(Some((role, points)));
+                }
+                None => {
+                    warnings.push(FabricationWarning::KerfCollapse {
+                        source: profile.source(),
+                    });
+                    compensated.push(None);
+                }
+            }
+        }
+
+        // Greedy nearest-neighbour order; a loop becomes available once
+        // everything nested inside it has been cut.
+        let mut remaining_children = vec![0usize; loops.len()];
+        for p in parents.iter().flatten() {
+            remaining_children[*p] += 1;
+        }
+        let mut done = vec![false; loops.len()];
+        let mut open_done = vec![false; open.len()];
+        let mut current = Point2D::new(0.0, 0.0);
+        let mut cuts = Vec::new();
+
+        loop {
+            let mut best: Option<(f64, Candidate)> = None;
+            for i in (0..loops.len()).filter(|&i| !done[i] && remaining_children[i] == 0) {
+                let (distance, vertex) = match &compensated[i] {
+                    Some((_, points)) => nearest_vertex(points, &current),
+                    None => (0.0, 0),
+                };
+                if best.as_ref().is_none_or(|(d, _)| distance < *d) {
+                    best = Some((distance, Candidate::Loop(i, vertex)));
+                }
+            }
+            for (i, profile) in open.iter().enumerate().filter(|(i, _)| !open_done[*i]) {
+                let first = profile.points[0].distance_to(&current);
+                let last = profile.points.last().unwrap().distance_to(&current);
+                let (distance, reversed) = if last < first {
+                    (last, true)
+                } else {
+                    (first, false)
+                };
+                if best.as_ref().is_none_or(|(d, _)| distance < *d) {
+                    best = Some((distance, Candidate::Open(i, reversed)));
+                }
+            }
+
+            match best {
+                None => break,
+                Some((_, Candidate::Loop(i, vertex))) => {
+                    done[i] = true;
+                    if let Some(p) = parents[i] {
+                        remaining_children[p] -= 1;
+                    }
+                    let Some((role, points)) = &compensated[i] else {
+                        continue;
+                    };
+                    let mut path = points.clone();
+                    path.rotate_left(vertex);
+                    let (lead_in, lead_out, shortened) = leads(&path, *role, &settings);
+                    if let Some(length) = shortened {
+                        warnings.push(FabricationWarning::LeadShortened {
+                            source: loops[i].source(),
+                            length,
+                        });
+                    }
+                    path.push(path[0]);
+                    let cut = CutPath {
+                        sources: loops[i].sources.clone(),
+                        role: *role,
+                        lead_in,
+                        path,
+                        lead_out,
+                    };
+                    current = cut.end_point();
+                    cuts.push(cut);
+                }
+                Some((_, Candidate::Open(i, reversed))) => {
+                    open_done[i] = true;
+                    let mut path = open[i].points.clone();
+                    if reversed {
+                        path.reverse();
+                    }
+                    let cut = CutPath {
+                        sources: open[i].sources.clone(),
+                        role: ContourRole::Open,
+                        lead_in: Vec::new(),
+                        path,
+                        lead_out: Vec::new(),
+                    };
+                    current = cut.end_point();
+                    cuts.push(cut);
+                }
+            }
+        }
+
+        Self {
+            settings,
+            cuts,
+            warnings,
+        }
+    }
+}
+
+/// Next cut considered by the ordering pass
+enum Candidate {
+    /// Closed loop index and starting vertex
+    Loop(usize, usize),
+    /// Open profile index and whether to cut it backwards
+    Open(usize, bool),
+}
+
+/// Profile assembled from drawing entities
+#[derive(Debug, Clone)]
+struct Profile {
+    points: Vec<Point2D>,
+    closed: bool,
+    sources: Vec<Uuid>,
+}
+
+impl Profile {
+    fn source(&self) -> Option<Uuid> {
+        self.sources.first().copied()
+    }
+}
+
+fn collect_profiles(
+    doc: &Document,
+    settings: &CuttingSettings,
+    warnings: &mut Vec<FabricationWarning>,
+) -> Vec<Profile> {
+    let tol = settings.chord_tolerance;
+    let mut profiles = Vec::new();
+    let mut pieces = Vec::new();
+
+    for entity in &doc.entities {
+        let layer_hidden = doc
+            .get_layer(&entity.layer)
+            .is_some_and(|l| !l.visible || l.frozen);
+        if !entity.visible || layer_hidden {
+            continue;
+        }
+        let piece = |points: Vec<Point2D>, closed: bool| Profile {
+            points,
+            closed,
+            sources: vec![entity.id],
+        };
+
+        match &entity.geometry {
+            GeometryType::Line(l) => pieces.push(piece(vec![point(l.start), point(l.end)], false)),
+            GeometryType::Arc(a) => {
+                let mut sweep = a.end_angle - a.start_angle;
+                while sweep <= 0.0 {
+                    sweep += TAU;
+                }
+                let points = arc_points(point(a.center), a.radius, a.start_angle, sweep, tol);
+                pieces.push(piece(points, false));
+            }
+            GeometryType::Circle(c) => {
+                let mut points = arc_points(point(c.center), c.radius, 0.0, TAU, tol);
+                points.pop();
+                profiles.push(piece(points, true));
+            }
+            GeometryType::Ellipse(e) => {
+                profiles.push(piece(ellipse_points(e, tol), true));
+            }
+            GeometryType::Polyline(p) => {
+                let points = polyline_points(p, tol);
+                if p.closed {
+                    profiles.push(piece(points, true));
+                } else {
+                    pieces.push(piece(points, false));
+                }
+            }
+            GeometryType::Spline(s) => match to_curve(s) {
+                Some(curve) => {
+                    let mut points = match &curve {
+                        Ok(spline) => flatten(spline, tol),
+                        Err(nurbs) => flatten(nurbs, tol),
+                    };
+                    let closed = points.len() > 3 && points[0].distance_to(&points[points.len() - 1]) <= 1e-9;
+                    if closed {
+                        points.pop();
+                        profiles.push(piece(points, true));
+                    } else {
+                        pieces.push(piece(points, false));
+                    }
+                }
+                // Knots that don't match the control points
+                None => warnings.push(FabricationWarning::UnsupportedEntity {
+                    source: entity.id,
+                    kind: entity.geometry.type_name().to_string(),
+                }),
+            },
+            // Annotation never gets cut
+            _ => {}
+        }
+    }
+
+    profiles.extend(chain(pieces, settings.join_tolerance));
+    profiles
+}
+
+/// Join open pieces end to end; pieces that meet themselves become loops
+fn chain(mut pieces: Vec<Profile>, tol: f64) -> Vec<Profile> {
+    let mut out = Vec::new();
+    while let Some(mut current) = pieces.pop() {
+        loop {
+            let start = current.points[0];
+            let end = *current.points.last().unwrap();
+            if current.points.len() > 2 && start.distance_to(&end) <= tol {
+                current.points.pop();
+                current.closed = true;
+                break;
+            }
+
+            let touches = |p: &Profile, q: &Point2D| {
+                p.points[0].distance_to(q) <= tol || p.points.last().unwrap().distance_to(q) <= tol
+            };
+            if let Some(i) = pieces.iter().position(|p| touches(p, &end)) {
+                let mut next = pieces.swap_remove(i);
+                if next.points[0].distance_to(&end) > tol {
+                    next.points.reverse();
+                }
+                current.points.extend(next.points.into_iter().skip(1));
+                current.sources.extend(next.sources);
+            } else if let Some(i) = pieces.iter().position(|p| touches(p, &start)) {
+                let mut prev = pieces.swap_remove(i);
+                if prev.points.last().unwrap().distance_to(&start) > tol {
+                    prev.points.reverse();
+                }
+                prev.points.pop();
+                prev.points.append(&mut current.points);
+                prev.sources.append(&mut current.sources);
+                current.points = prev.points;
+                current.sources = prev.sources;
+            } else {
+                break;
+            }
+        }
+        out.push(current);
+    }
+    out
+}
+
+/// Lead-in and lead-out for a closed path starting at `path[0]`
+///
+/// Paths run with the part on the right, so the scrap is always on the
+/// left of travel. Leads that would leave the scrap are shortened; the
+/// third value reports the length used when that happens.
+fn leads(
+    path: &[Point2D],
+    role: ContourRole,
+    settings: &CuttingSettings,
+) -> (Vec<Point2D>, Vec<Point2D>, Option<f64>) {
+    if settings.lead_style == LeadStyle::None {
+        return (Vec::new(), Vec::new(), None);
+    }
+
+    let n = path.len();
+    let v = path[0];
+    let outgoing = unit(&v, &path[1 % n]);
+    let incoming = unit(&path[n - 1], &v);
+    let polygon = Polygon2D::new(path.to_vec());
+    let in_scrap = |p: &Point2D| polygon.contains_point(p) == (role == ContourRole::Hole);
+
+    let mut shortened = None;
+    let mut lead_in = Vec::new();
+    let mut length = settings.lead_in_length;
+    for _ in 0..4 {
+        let candidate = lead_points(v, incoming, outgoing, length, settings.lead_style, true);
+        if candidate.iter().take(candidate.len() - 1).all(&in_scrap) {
+            lead_in = candidate;
+            break;
+        }
+        length /= 2.0;
+    }
+    if lead_in.is_empty() {
+        length = 0.0;
+    }
+    if length < settings.lead_in_length {
+        shortened = Some(length);
+    }
+
+    let mut lead_out = Vec::new();
+    let mut length = settings.lead_out_length;
+    for _ in 0..4 {
+        let candidate = lead_points(v, incoming, outgoing, length, settings.lead_style, false);
+        if candidate.iter().skip(1).all(&in_scrap) {
+            lead_out = candidate;
+            break;
+        }
+        length /= 2.0;
+    }
+    if lead_out.len() < 2 {
+        lead_out.clear();
+    }
+
+    (lead_in, lead_out, shortened)
+}
+
+/// Lead geometry at vertex `v`; lead-ins end at `v`, lead-outs start there
+fn lead_points(
+    v: Point2D,
+    incoming: (f64, f64),
+    outgoing: (f64, f64),
+    length: f64,
+    style: LeadStyle,
+    lead_in: bool,
+) -> Vec<Point2D> {
+    if length <= 0.0 {
+        return vec![v];
+    }
+    let left = |(x, y): (f64, f64)| (-y, x);
+    match style {
+        LeadStyle::Arc => {
+            // Quarter circle on the scrap side, tangent to the path at `v`
+            let dir = if lead_in { outgoing } else { incoming };
+            let (nx, ny) = left(dir);
+            let center = Point2D::new(v.x + nx * length, v.y + ny * length);
+            let start = if lead_in {
+                (-dir.1).atan2(-dir.0)
+            } else {
+                (-ny).atan2(-nx)
+            };
+            let steps = 8;
+            (0..=steps)
+                .map(|i| {
+                    let a = start + FRAC_PI_2 * i as f64 / steps as f64;
+                    Point2D::new(center.x + length * a.cos(), center.y + length * a.sin())
+                })
+                .collect()
+        }
+        _ => {
+            // Straight along the bisector of the two scrap-side normals
+            let (ax, ay) = left(incoming);
+            let (bx, by) = left(outgoing);
+            let (mut nx, mut ny) = (ax + bx, ay + by);
+            let len = (nx * nx + ny * ny).sqrt();
+            if len < 1e-9 {
+                (nx, ny) = (bx, by);
+            } else {
+                (nx, ny) = (nx / len, ny / len);
+            }
+            let outside = Point2D::new(v.x + nx * length, v.y + ny * length);
+            if lead_in {
+                vec![outside, v]
+            } else {
+                vec![v, outside]
+            }
+        }
+    }
+}
+
+fn nearest_vertex(points: &[Point2D], from: &Point2D) -> (f64, usize) {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p.distance_to(from), i))
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .unwrap_or((f64::INFINITY, 0))
+}
+
+fn unit(a: &Point2D, b: &Point2D) -> (f64, f64) {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let len = (dx * dx + dy * dy).sqrt().max(1e-12);
+    (dx / len, dy / len)
+}
+
+fn path_length(points: &[Point2D]) -> f64 {
+    points.windows(2).map(|w| w[0].distance_to(&w[1])).sum()
+}
+
+fn point(v: Vec3) -> Point2D {
+    Point2D::new(v.x, v.y)
+}
+
+/// Points along a circular arc, `sweep` radians from `start` (negative = CW)
+fn arc_points(center: Point2D, radius: f64, start: f64, sweep: f64, tol: f64) -> Vec<Point2D> {
+    let n = arc_segments(radius, sweep, tol);
+    (0..=n)
+        .map(|i| {
+            let a = start + sweep * i as f64 / n as f64;
+            Point2D::new(center.x + radius * a.cos(), center.y + radius * a.sin())
+        })
+        .collect()
+}
+
+fn arc_segments(radius: f64, sweep: f64, tol: f64) -> usize {
+    let step = if radius > tol {
+        2.0 * (1.0 - tol / radius).acos()
+    } else {
+        FRAC_PI_2
+    };
+    let minimum = (sweep.abs() / TAU * 8.0).ceil() as usize;
+    ((sweep.abs() / step).ceil() as usize).clamp(minimum.max(1), 4096)
+}
+
+fn ellipse_points(e: &Ellipse, tol: f64) -> Vec<Point2D> {
+    let n = arc_segments(e.major_axis.max(e.minor_axis), TAU, tol);
+    let (sin, cos) = e.rotation.sin_cos();
+    (0..n)
+        .map(|i| {
+            let t = TAU * i as f64 / n as f64;
+            let (x, y) = (e.major_axis * t.cos(), e.minor_axis * t.sin());
+            Point2D::new(
+                e.center.x + x * cos - y * sin,
+                e.center.y + x * sin + y * cos,
+            )
+        })
+        .collect()
+}
+
+/// Flatten a polyline, expanding bulged segments into arcs
+fn polyline_points(polyline: &Polyline, tol: f64) -> Vec<Point2D> {
+    let vertices = &polyline.vertices;
+    let n = vertices.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let segments = if polyline.closed { n } else { n - 1 };
+    let mut points = vec![point(vertices[0].position)];
+    for i in 0..segments {
+        let a = point(vertices[i].position);
+        let b = point(vertices[(i + 1) % n].position);
+        let bulge = vertices[i].bulge;
+        let chord = a.distance_to(&b);
+        if bulge.abs() < 1e-10 || chord < 1e-12 {
+            points.push(b);
+            continue;
+        }
+        // Included angle is 4·atan(bulge), positive counter-clockwise
+        let sweep = 4.0 * bulge.atan();
+        let radius = chord / (2.0 * (sweep / 2.0).sin()).abs();
+        let offset = (chord / 2.0) / (sweep / 2.0).tan();
+        let (lx, ly) = ((a.y - b.y) / chord, (b.x - a.x) / chord);
+        let center = Point2D::new(
+            (a.x + b.x) / 2.0 + lx * offset,
+            (a.y + b.y) / 2.0 + ly * offset,
+        );
+        let start = (a.y - center.y).atan2(a.x - center.x);
+        points.extend(
+            arc_points(center, radius, start, sweep, tol)
+                .into_iter()
+                .skip(1),
+        );
+    }
+    points
+}
+
+fn cut_layer(name: &str, color: Color) -> Layer {
+    Layer {
+        name: name.to_string(),
+        color,
+        line_type: LineType::Continuous,
+        line_weight: LineWeight::Default,
+        visible: true,
+        locked: false,
+        frozen: false,
+        plottable: true,
+        confidential: false,
+    }
+}
+
+fn polyline_entity(points: &[Point2D], closed: bool, layer: &str) -> Entity {
+    let vertices = points
+        .iter()
+        .map(|p| Vertex {
+            position: Vec3::new(p.x, p.y, 0.0),
+            bulge: 0.0,
+        })
+        .collect();
+    Entity::new(
+        GeometryType::Polyline(Polyline { vertices, closed }),
+        layer.to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{Circle, Line, Spline};
+
+    fn rect(x0: f64, y0: f64, x1: f64, y1: f64) -> Vec<Point2D> {
+        vec![
+            Point2D::new(x0, y0),
+            Point2D::new(x1, y0),
+            Point2D::new(x1, y1),
+            Point2D::new(x0, y1),
+        ]
+    }
+
+    fn area(points: &[Point2D]) -> f64 {
+        signed_area(&dedup_closing_point(points, true))
+    }
+
+    #[test]
+    fn test_plate_with_hole() {
+        let mut settings = CuttingSettings::plasma();
+        settings.kerf_width = 2.0;
+        let job = CuttingJob::from_profiles(
+            &[rect(0.0, 0.0, 100.0, 50.0), rect(40.0, 20.0, 60.0, 30.0)],
+            settings,
+        )
+        .unwrap();
+
+        assert_eq!(job.pierce_count(), 2);
+        // Hole first, shrunk by half the kerf and run counter-clockwise
+        assert_eq!(job.cuts[0].role, ContourRole::Hole);
+        assert!((area(&job.cuts[0].path) - 18.0 * 8.0).abs() < 1e-6);
+        // Outline last, grown by half the kerf and run clockwise
+        assert_eq!(job.cuts[1].role, ContourRole::Outline);
+        assert!((area(&job.cuts[1].path) + 102.0 * 52.0).abs() < 1e-6);
+
+        // Pierces sit in the scrap: inside the hole, outside the plate
+        let hole_pierce = job.cuts[0].pierce_point();
+        assert!(hole_pierce.x > 41.0 && hole_pierce.x < 59.0);
+        assert!(hole_pierce.y > 21.0 && hole_pierce.y < 29.0);
+        let outline_pierce = job.cuts[1].pierce_point();
+        assert!(!Polygon2D::new(rect(-1.0, -1.0, 101.0, 51.0)).contains_point(&outline_pierce));
+    }
+
+    #[test]
+    fn test_nearest_neighbour_order() {
+        let job = CuttingJob::from_profiles(
+            &[
+                rect(200.0, 0.0, 210.0, 10.0),
+                rect(0.0, 0.0, 10.0, 10.0),
+                rect(100.0, 0.0, 110.0, 10.0),
+            ],
+            CuttingSettings::laser(),
+        )
+        .unwrap();
+        let xs: Vec<f64> = job.cuts.iter().map(|c| c.path[0].x.round()).collect();
+        assert_eq!(xs, vec![0.0, 100.0, 200.0]);
+        assert!(job.travel_length() < 250.0);
+    }
+
+    #[test]
+    fn test_small_features_and_collapse() {
+        let job = CuttingJob::from_profiles(
+            &[rect(0.0, 0.0, 50.0, 50.0), rect(10.0, 10.0, 11.0, 11.0)],
+            CuttingSettings::plasma(),
+        )
+        .unwrap();
+
+        assert!(job
+            .warnings
+            .iter()
+            .any(|w| matches!(w, FabricationWarning::SmallFeature { size, .. } if (*size - 1.0).abs() < 1e-9)));
+        // A 1 mm hole vanishes under a 1.5 mm kerf
+        assert!(job
+            .warnings
+            .iter()
+            .any(|w| matches!(w, FabricationWarning::KerfCollapse { .. })));
+        assert_eq!(job.pierce_count(), 1);
+    }
+
+    #[test]
+    fn test_from_document_chains_lines() {
+        let mut doc = Document::new();
+        let corners = [(0.0, 0.0), (80.0, 0.0), (80.0, 40.0), (0.0, 40.0)];
+        for i in [2, 0, 3, 1] {
+            let (x0, y0) = corners[i];
+            let (x1, y1) = corners[(i + 1) % 4];
+            doc.add_entity(Entity::new(
+                GeometryType::Line(Line {
+                    start: Vec3::new(x0, y0, 0.0),
+                    end: Vec3::new(x1, y1, 0.0),
+                }),
+                "0".to_string(),
+            ));
+        }
+        doc.add_entity(Entity::new(
+            GeometryType::Circle(Circle {
+                center: Vec3::new(20.0, 20.0, 0.0),
+                radius: 5.0,
+                normal: Vec3::unit_z(),
+            }),
+            "0".to_string(),
+        ));
+        let spline = doc.add_entity(Entity::new(
+            GeometryType::Spline(Spline {
+                degree: 3,
+                control_points: vec![Vec3::zero(); 4],
+                knots: Vec::new(),
+                weights: None,
+                closed: false,
+            }),
+            "0".to_string(),
+        ));
+
+        let job = CuttingJob::from_document(&doc, CuttingSettings::laser()).unwrap();
+        assert_eq!(job.pierce_count(), 2);
+        assert_eq!(job.cuts[0].role, ContourRole::Hole);
+        assert_eq!(job.cuts[1].role, ContourRole::Outline);
+        assert_eq!(job.cuts[1].sources.len(), 4);
+        assert!(job.warnings.iter().any(
+            |w| matches!(w, FabricationWarning::UnsupportedEntity { source, .. } if *source == spline)
+        ));
+
+        let dxf = job.to_dxf().unwrap();
+        assert!(dxf.contains("LWPOLYLINE"));
+        assert!(dxf.contains(CUT_LAYER));
+        assert!(dxf.contains(LEAD_LAYER));
+    }
+
+    #[test]
+    fn test_bulge_polyline_flattening() {
+        // Two half-circle bulges make a full circle of radius 5
+        let polyline = Polyline {
+            vertices: vec![
+                Vertex {
+                    position: Vec3::new(-5.0, 0.0, 0.0),
+                    bulge: 1.0,
+                },
+                Vertex {
+                    position: Vec3::new(5.0, 0.0, 0.0),
+                    bulge: 1.0,
+                },
+            ],
+            closed: true,
+        };
+        let points = polyline_points(&polyline, 0.001);
+        for p in &points {
+            assert!((p.distance_to_origin() - 5.0).abs() < 1e-9);
+        }
+        let loop_points = dedup_closing_point(&points, true);
+        assert!((signed_area(&loop_points) - std::f64::consts::PI * 25.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_gcode_output() {
+        let profiles = [rect(0.0, 0.0, 20.0, 20.0)];
+
+        let laser = CuttingJob::from_profiles(&profiles, CuttingSettings::laser()).unwrap();
+        let gcode = laser.to_gcode(&PostProcessor::new(Controller::Grbl));
+        assert!(gcode.contains("M4 S1000"));
+        assert!(!gcode.contains(" Z"));
+        assert!(gcode.contains("M05"));
+
+        let plasma = CuttingJob::from_profiles(&profiles, CuttingSettings::plasma()).unwrap();
+        let gcode = plasma.to_gcode(&PostProcessor::new(Controller::LinuxCnc));
+        assert!(gcode.contains("Z3.800"));
+        assert!(gcode.contains("Z1.500"));
+        assert!(gcode.contains("G4 P0.50"));
+        assert!(gcode.lines().any(|l| l == "M03"));
+    }
+}
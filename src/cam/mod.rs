@@ -0,0 +1,77 @@
+//! # CADDY CAM
+//!
+//! 2.5D toolpath generation from 2D profiles:
+//!
+//! - **Tools**: end mills and drills with default feeds and speeds
+//! - **Operations**: contour (inside/outside/on), pocket (offset or zig-zag,
+//!   with islands), and drilling (simple, dwell, peck)
+//! - **Post-processing**: G-code for Grbl, LinuxCNC, Mach3, and Fanuc
+//! - **Thermal cutting**: laser and plasma jobs with kerf compensation,
+//!   lead-ins, and cut ordering, exported as DXF or G-code
+//!
+//! Coordinates are in millimeters with Z = 0 at the stock top.
+//!
+//! ## Example
+//!
+//! ```
+//! use caddy::cam::{ContourOperation, ContourSide, Controller, PostProcessor, Tool};
+//! use caddy::geometry::{Point2D, Polyline2D};
+//!
+//! let profile = Polyline2D::closed(vec![
+//!     Point2D::new(0.0, 0.0),
+//!     Point2D::new(50.0, 0.0),
+//!     Point2D::new(50.0, 30.0),
+//!     Point2D::new(0.0, 30.0),
+//! ]);
+//! let op = ContourOperation::new("Outline", profile, ContourSide::Outside);
+//! let path = op.generate(&Tool::end_mill(1, 6.0)).unwrap();
+//! let gcode = PostProcessor::new(Controller::Grbl).post(&[path]).unwrap();
+//! assert!(gcode.contains("M30"));
+//! ```
+
+pub mod cutting;
+pub mod operations;
+pub mod post;
+pub mod tool;
+pub mod toolpath;
+
+use thiserror::Error;
+
+pub use cutting::{
+    ContourRole, CutPath, CuttingJob, CuttingProcess, CuttingSettings, FabricationWarning,
+    LeadStyle,
+};
+pub use operations::{
+    ContourOperation, ContourSide, CutDirection, CutParameters, DrillCycleKind, DrillOperation,
+    PocketOperation, PocketStrategy,
+};
+pub use post::{Controller, PostProcessor};
+pub use tool::{Tool, ToolKind, ToolLibrary};
+pub use toolpath::{DrillCycle, Move, Toolpath};
+
+/// CAM errors
+#[derive(Debug, Error)]
+pub enum CamError {
+    /// Tool definition is unusable for the operation
+    #[error("Invalid tool: {0}")]
+    InvalidTool(String),
+
+    /// Cutting parameters are out of range
+    #[error("Invalid parameters: {0}")]
+    InvalidParameters(String),
+
+    /// Input geometry cannot be machined
+    #[error("Invalid geometry: {0}")]
+    InvalidGeometry(String),
+
+    /// Combination of options is not supported
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
+
+    /// Writing an output format failed
+    #[error("Export failed: {0}")]
+    Export(String),
+}
+
+/// Result type for CAM operations
+pub type CamResult<T> = Result<T, CamError>;
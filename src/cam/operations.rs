@@ -0,0 +1,925 @@
+//! Machining operations
+//!
+//! Each operation takes 2D geometry plus cutting parameters and produces a
+//! [`Toolpath`] for a given [`Tool`]:
+//!
+//! - [`ContourOperation`]: follow a profile inside, outside, or on the line
+//! - [`PocketOperation`]: clear the area inside a boundary, avoiding islands
+//! - [`DrillOperation`]: drill a set of holes with an optional peck cycle
+
+use crate::geometry::{Point2D, Polygon2D, Polyline2D};
+use serde::{Deserialize, Serialize};
+
+use super::tool::Tool;
+use super::toolpath::{DrillCycle, Move, Toolpath};
+use super::{CamError, CamResult};
+
+/// Depth and stepping parameters shared by milling operations
+///
+/// Z = 0 is the stock top; depths are given as positive distances below it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CutParameters {
+    /// Clearance height for rapids between features
+    pub safe_z: f64,
+    /// Height where rapids stop and plunging starts
+    pub retract_z: f64,
+    /// Total cut depth below the stock top
+    pub final_depth: f64,
+    /// Maximum depth per pass
+    pub stepdown: f64,
+    /// Radial step as a fraction of the tool diameter (0, 1]
+    pub stepover: f64,
+    /// Feed rate override (tool default when `None`)
+    pub feed_rate: Option<f64>,
+    /// Plunge rate override (tool default when `None`)
+    pub plunge_rate: Option<f64>,
+}
+
+impl Default for CutParameters {
+    fn default() -> Self {
+        Self {
+            safe_z: 5.0,
+            retract_z: 1.0,
+            final_depth: 3.0,
+            stepdown: 1.0,
+            stepover: 0.4,
+            feed_rate: None,
+            plunge_rate: None,
+        }
+    }
+}
+
+impl CutParameters {
+    /// Check that the parameters describe a valid cut
+    pub fn validate(&self) -> CamResult<()> {
+        if self.final_depth.is_nan() || self.final_depth <= 0.0 {
+            return Err(CamError::InvalidParameters(
+                "final depth must be positive".into(),
+            ));
+        }
+        if self.stepdown.is_nan() || self.stepdown <= 0.0 {
+            return Err(CamError::InvalidParameters(
+                "stepdown must be positive".into(),
+            ));
+        }
+        if self.stepover.is_nan() || self.stepover <= 0.0 || self.stepover > 1.0 {
+            return Err(CamError::InvalidParameters(
+                "stepover must be in (0, 1] of the tool diameter".into(),
+            ));
+        }
+        if self.retract_z < 0.0 || self.safe_z < self.retract_z {
+            return Err(CamError::InvalidParameters(
+                "expected 0 <= retract_z <= safe_z".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Z levels of successive passes, ending exactly at the final depth
+    pub fn depth_levels(&self) -> Vec<f64> {
+        let passes = (self.final_depth / self.stepdown - 1e-9).ceil().max(1.0) as usize;
+        (1..=passes)
+            .map(|i| -(i as f64 * self.stepdown).min(self.final_depth))
+            .collect()
+    }
+
+    fn feed(&self, tool: &Tool) -> f64 {
+        self.feed_rate.unwrap_or(tool.feed_rate)
+    }
+
+    fn plunge(&self, tool: &Tool) -> f64 {
+        self.plunge_rate.unwrap_or(tool.plunge_rate)
+    }
+}
+
+/// Which side of the profile the tool runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContourSide {
+    /// Tool outside a closed profile (cutting a part out)
+    Outside,
+    /// Tool inside a closed profile (cutting a hole)
+    Inside,
+    /// Tool center on the profile (engraving, open profiles)
+    On,
+}
+
+/// Milling direction relative to spindle rotation (assumes M03, clockwise)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CutDirection {
+    /// Climb milling (better finish on rigid machines)
+    Climb,
+    /// Conventional milling (safer on machines with backlash)
+    Conventional,
+}
+
+/// Profile-following operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContourOperation {
+    /// Operation name
+    pub name: String,
+    /// Profile to follow
+    pub profile: Polyline2D,
+    /// Tool side
+    pub side: ContourSide,
+    /// Milling direction
+    pub direction: CutDirection,
+    /// Depth and feed parameters
+    pub params: CutParameters,
+}
+
+impl ContourOperation {
+    /// Create a contour operation with default parameters
+    pub fn new(name: impl Into<String>, profile: Polyline2D, side: ContourSide) -> Self {
+        Self {
+            name: name.into(),
+            profile,
+            side,
+            direction: CutDirection::Climb,
+            params: CutParameters::default(),
+        }
+    }
+
+    /// Generate the toolpath for `tool`
+    pub fn generate(&self, tool: &Tool) -> CamResult<Toolpath> {
+        tool.validate()?;
+        self.params.validate()?;
+        if !tool.kind.can_side_cut() {
+            return Err(CamError::InvalidTool(format!(
+                "{} cannot be used for contouring",
+                tool.name
+            )));
+        }
+
+        let mut points = dedup_closing_point(&self.profile.vertices, self.profile.closed);
+        if points.len() < 2 {
+            return Err(CamError::InvalidGeometry(
+                "profile needs at least two points".into(),
+            ));
+        }
+        if !self.profile.closed && self.side != ContourSide::On {
+            return Err(CamError::InvalidGeometry(
+                "open profiles can only be cut with the tool on the line".into(),
+            ));
+        }
+
+        if self.profile.closed {
+            if points.len() < 3 {
+                return Err(CamError::InvalidGeometry(
+                    "closed profile needs three points".into(),
+                ));
+            }
+            make_ccw(&mut points);
+            let offset = match self.side {
+                ContourSide::Outside => tool.radius(),
+                ContourSide::Inside => -tool.radius(),
+                ContourSide::On => 0.0,
+            };
+            if offset != 0.0 {
+                points = offset_loop(&points, offset).ok_or_else(|| {
+                    CamError::InvalidGeometry(format!(
+                        "profile is too small for a {} diameter tool",
+                        tool.diameter
+                    ))
+                })?;
+            }
+
+            // With a clockwise spindle, climb milling runs clockwise around
+            // material on the inside of the path and counter-clockwise
+            // around material on the outside.
+            let clockwise = match (self.side, self.direction) {
+                (ContourSide::Inside, CutDirection::Climb) => false,
+                (ContourSide::Inside, CutDirection::Conventional) => true,
+                (_, CutDirection::Climb) => true,
+                (_, CutDirection::Conventional) => false,
+            };
+            if clockwise {
+                points.reverse();
+            }
+        }
+
+        let mut path = Toolpath::new(&self.name, tool.clone(), self.params.safe_z);
+        let feed = self.params.feed(tool);
+        let plunge = self.params.plunge(tool);
+        let start = points[0];
+
+        path.rapid(start.x, start.y, self.params.safe_z);
+        path.rapid(start.x, start.y, self.params.retract_z);
+        for z in self.params.depth_levels() {
+            if !self.profile.closed {
+                // Open profiles restart from the same end every pass
+                if let Some(current) = path.current_position() {
+                    if (current.x - start.x).abs() > 1e-9 || (current.y - start.y).abs() > 1e-9 {
+                        path.rapid(current.x, current.y, self.params.retract_z);
+                        path.rapid(start.x, start.y, self.params.retract_z);
+                    }
+                }
+            }
+            path.feed(start.x, start.y, z, plunge);
+            for p in points.iter().skip(1) {
+                path.feed(p.x, p.y, z, feed);
+            }
+            if self.profile.closed {
+                path.feed(start.x, start.y, z, feed);
+            }
+        }
+        retract(&mut path);
+
+        Ok(path)
+    }
+}
+
+/// Area clearing strategy
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PocketStrategy {
+    /// Concentric rings offset from the boundary, cut from the center outward
+    Offset,
+    /// Parallel raster passes at an angle (degrees), alternating direction
+    ZigZag {
+        /// Raster angle in degrees from the X axis
+        angle: f64,
+    },
+}
+
+/// Area clearing operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PocketOperation {
+    /// Operation name
+    pub name: String,
+    /// Pocket outline; `holes` are islands left standing
+    pub boundary: Polygon2D,
+    /// Clearing strategy
+    pub strategy: PocketStrategy,
+    /// Finish with a pass along the walls and islands (zig-zag only)
+    pub finish_pass: bool,
+    /// Depth and stepping parameters
+    pub params: CutParameters,
+}
+
+impl PocketOperation {
+    /// Create a pocket operation with default parameters
+    pub fn new(name: impl Into<String>, boundary: Polygon2D, strategy: PocketStrategy) -> Self {
+        Self {
+            name: name.into(),
+            boundary,
+            strategy,
+            finish_pass: true,
+            params: CutParameters::default(),
+        }
+    }
+
+    /// Generate the toolpath for `tool`
+    pub fn generate(&self, tool: &Tool) -> CamResult<Toolpath> {
+        tool.validate()?;
+        self.params.validate()?;
+        if !tool.kind.can_side_cut() {
+            return Err(CamError::InvalidTool(format!(
+                "{} cannot be used for pocketing",
+                tool.name
+            )));
+        }
+
+        let mut outer = dedup_closing_point(&self.boundary.vertices, true);
+        if outer.len() < 3 {
+            return Err(CamError::InvalidGeometry(
+                "pocket boundary needs three points".into(),
+            ));
+        }
+        make_ccw(&mut outer);
+
+        let step = tool.diameter * self.params.stepover;
+        let passes = match self.strategy {
+            PocketStrategy::Offset => {
+                if !self.boundary.holes.is_empty() {
+                    return Err(CamError::Unsupported(
+                        "pockets with islands require the zig-zag strategy".into(),
+                    ));
+                }
+                offset_rings(&outer, tool.radius(), step)?
+            }
+            PocketStrategy::ZigZag { angle } => {
+                self.zigzag_passes(&outer, tool.radius(), step, angle.to_radians())?
+            }
+        };
+
+        let mut path = Toolpath::new(&self.name, tool.clone(), self.params.safe_z);
+        let feed = self.params.feed(tool);
+        let plunge = self.params.plunge(tool);
+
+        for z in self.params.depth_levels() {
+            let mut down = false;
+            for pass in &passes {
+                let first = pass.points[0];
+                if !(down && pass.linked) {
+                    if let Some(current) = path.current_position() {
+                        path.rapid(current.x, current.y, self.params.retract_z);
+                    }
+                    path.rapid(first.x, first.y, self.params.safe_z);
+                    path.rapid(first.x, first.y, self.params.retract_z);
+                    path.feed(first.x, first.y, z, plunge);
+                } else {
+                    path.feed(first.x, first.y, z, feed);
+                }
+                for p in pass.points.iter().skip(1) {
+                    path.feed(p.x, p.y, z, feed);
+                }
+                down = true;
+            }
+        }
+        retract(&mut path);
+
+        Ok(path)
+    }
+
+    fn zigzag_passes(
+        &self,
+        outer: &[Point2D],
+        radius: f64,
+        step: f64,
+        angle: f64,
+    ) -> CamResult<Vec<Pass>> {
+        let too_small =
+            || CamError::InvalidGeometry("pocket is too small for the selected tool".into());
+
+        let wall = offset_loop(outer, -radius).ok_or_else(too_small)?;
+        let mut loops = vec![wall.clone()];
+        let mut islands = Vec::new();
+        for hole in &self.boundary.holes {
+            let mut island = dedup_closing_point(hole, true);
+            if island.len() < 3 {
+                continue;
+            }
+            make_ccw(&mut island);
+            let grown = offset_loop(&island, radius).ok_or_else(too_small)?;
+            islands.push(grown.clone());
+            loops.push(grown);
+        }
+
+        // Work in a frame where the raster lines are horizontal
+        let (sin, cos) = angle.sin_cos();
+        let to_local = |p: &Point2D| Point2D::new(p.x * cos + p.y * sin, -p.x * sin + p.y * cos);
+        let to_world = |p: &Point2D| Point2D::new(p.x * cos - p.y * sin, p.x * sin + p.y * cos);
+        let local: Vec<Vec<Point2D>> = loops
+            .iter()
+            .map(|l| l.iter().map(to_local).collect())
+            .collect();
+
+        let (min_y, max_y) = local
+            .iter()
+            .flatten()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+                (lo.min(p.y), hi.max(p.y))
+            });
+
+        let mut passes: Vec<Pass> = Vec::new();
+        let mut previous_row: Vec<(f64, f64)> = Vec::new();
+        let rows = ((max_y - min_y) / step).floor() as usize;
+        for row in 0..=rows {
+            // Nudge off the extremes so scanlines never graze a vertex
+            let y = (min_y + row as f64 * step).clamp(min_y + 1e-7, max_y - 1e-7);
+            let mut xs = scanline_crossings(&local, y);
+            xs.sort_by(|a, b| a.total_cmp(b));
+            let mut spans: Vec<(f64, f64)> = xs.chunks_exact(2).map(|c| (c[0], c[1])).collect();
+            if row % 2 == 1 {
+                spans.reverse();
+            }
+
+            let mut current_row = Vec::new();
+            for &(a, b) in &spans {
+                let (x0, x1) = if row % 2 == 1 { (b, a) } else { (a, b) };
+                // Stay down only when the previous row's span overlaps this
+                // one, so the connecting move runs through cleared material.
+                let linked = current_row.is_empty()
+                    && previous_row.len() == 1
+                    && spans.len() == 1
+                    && overlaps(previous_row[0], (a, b));
+                passes.push(Pass {
+                    points: vec![
+                        to_world(&Point2D::new(x0, y)),
+                        to_world(&Point2D::new(x1, y)),
+                    ],
+                    linked,
+                });
+                current_row.push((a, b));
+            }
+            previous_row = current_row;
+        }
+
+        if self.finish_pass {
+            passes.push(Pass::closed(wall));
+            for mut island in islands {
+                // Climb milling around an island runs clockwise
+                island.reverse();
+                passes.push(Pass::closed(island));
+            }
+        }
+
+        if passes.is_empty() {
+            return Err(too_small());
+        }
+        Ok(passes)
+    }
+}
+
+/// Hole drilling cycle
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DrillCycleKind {
+    /// Single plunge (G81)
+    Simple,
+    /// Plunge and dwell at the bottom for the given seconds (G82)
+    Dwell(f64),
+    /// Peck drilling with the given increment (G83)
+    Peck(f64),
+}
+
+/// Drilling operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrillOperation {
+    /// Operation name
+    pub name: String,
+    /// Hole centers
+    pub holes: Vec<Point2D>,
+    /// Hole depth below the stock top
+    pub depth: f64,
+    /// Drilling cycle
+    pub cycle: DrillCycleKind,
+    /// Clearance height
+    pub safe_z: f64,
+    /// R plane for the cycle
+    pub retract_z: f64,
+    /// Plunge feed override (tool plunge rate when `None`)
+    pub feed_rate: Option<f64>,
+    /// Reorder holes to shorten travel (nearest neighbour)
+    pub optimize_order: bool,
+}
+
+impl DrillOperation {
+    /// Create a drilling operation
+    pub fn new(name: impl Into<String>, holes: Vec<Point2D>, depth: f64) -> Self {
+        Self {
+            name: name.into(),
+            holes,
+            depth,
+            cycle: DrillCycleKind::Simple,
+            safe_z: 5.0,
+            retract_z: 1.0,
+            feed_rate: None,
+            optimize_order: true,
+        }
+    }
+
+    /// Generate the toolpath for `tool`
+    pub fn generate(&self, tool: &Tool) -> CamResult<Toolpath> {
+        tool.validate()?;
+        if self.depth.is_nan() || self.depth <= 0.0 {
+            return Err(CamError::InvalidParameters(
+                "drill depth must be positive".into(),
+            ));
+        }
+        if let DrillCycleKind::Peck(peck) = self.cycle {
+            if peck.is_nan() || peck <= 0.0 {
+                return Err(CamError::InvalidParameters(
+                    "peck increment must be positive".into(),
+                ));
+            }
+        }
+        if self.holes.is_empty() {
+            return Err(CamError::InvalidGeometry("no holes to drill".into()));
+        }
+
+        let order = if self.optimize_order {
+            nearest_neighbour_order(&self.holes)
+        } else {
+            (0..self.holes.len()).collect()
+        };
+
+        let mut path = Toolpath::new(&self.name, tool.clone(), self.safe_z);
+        let first = self.holes[order[0]];
+        path.rapid(first.x, first.y, self.safe_z);
+
+        let feed = self.feed_rate.unwrap_or(tool.plunge_rate);
+        for &i in &order {
+            let hole = self.holes[i];
+            path.moves.push(Move::Drill(DrillCycle {
+                x: hole.x,
+                y: hole.y,
+                bottom_z: -self.depth,
+                retract_z: self.retract_z,
+                peck: match self.cycle {
+                    DrillCycleKind::Peck(p) => Some(p),
+                    _ => None,
+                },
+                dwell: match self.cycle {
+                    DrillCycleKind::Dwell(s) => Some(s),
+                    _ => None,
+                },
+                feed,
+            }));
+        }
+        retract(&mut path);
+
+        Ok(path)
+    }
+}
+
+/// One continuous cutting pass
+#[derive(Debug, Clone)]
+struct Pass {
+    points: Vec<Point2D>,
+    /// Can be reached from the previous pass without lifting the tool
+    linked: bool,
+}
+
+impl Pass {
+    fn closed(mut points: Vec<Point2D>) -> Self {
+        let first = points[0];
+        points.push(first);
+        Self {
+            points,
+            linked: false,
+        }
+    }
+}
+
+fn retract(path: &mut Toolpath) {
+    if let Some(current) = path.current_position() {
+        path.rapid(current.x, current.y, path.safe_z);
+    }
+}
+
+fn overlaps(a: (f64, f64), b: (f64, f64)) -> bool {
+    a.0.max(b.0) < a.1.min(b.1)
+}
+
+/// X coordinates where the horizontal line at `y` crosses any loop edge
+fn scanline_crossings(loops: &[Vec<Point2D>], y: f64) -> Vec<f64> {
+    let mut xs = Vec::new();
+    for l in loops {
+        for i in 0..l.len() {
+            let a = l[i];
+            let b = l[(i + 1) % l.len()];
+            if (a.y <= y && b.y > y) || (b.y <= y && a.y > y) {
+                let t = (y - a.y) / (b.y - a.y);
+                xs.push(a.x + t * (b.x - a.x));
+            }
+        }
+    }
+    xs
+}
+
+/// Successive inward offsets of a CCW loop, innermost first
+fn offset_rings(outer: &[Point2D], radius: f64, step: f64) -> CamResult<Vec<Pass>> {
+    let mut rings = Vec::new();
+    let mut distance = radius;
+    while let Some(ring) = offset_loop(outer, -distance) {
+        rings.push(ring);
+        distance += step;
+        if rings.len() > 10_000 {
+            return Err(CamError::InvalidParameters(
+                "stepover too small for pocket size".into(),
+            ));
+        }
+    }
+    if rings.is_empty() {
+        return Err(CamError::InvalidGeometry(
+            "pocket is too small for the selected tool".into(),
+        ));
+    }
+
+    // Cut from the center outward; consecutive rings are at most one
+    // stepover apart, so linking moves stay inside cleared material.
+    rings.reverse();
+    let mut passes: Vec<Pass> = rings
+        .into_iter()
+        .map(|ring| {
+            // Rings stay counter-clockwise: climb milling with the
+            // material outside the path
+            let mut pass = Pass::closed(ring);
+            pass.linked = true;
+            pass
+        })
+        .collect();
+    passes[0].linked = false;
+    Ok(passes)
+}
+
+fn nearest_neighbour_order(points: &[Point2D]) -> Vec<usize> {
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    let mut order = Vec::with_capacity(points.len());
+    let mut current = Point2D::new(0.0, 0.0);
+    while !remaining.is_empty() {
+        let (k, _) = remaining
+            .iter()
+            .enumerate()
+            .map(|(k, &i)| (k, points[i].distance_squared_to(&current)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        let i = remaining.swap_remove(k);
+        current = points[i];
+        order.push(i);
+    }
+    order
+}
+
+pub(super) fn dedup_closing_point(points: &[Point2D], closed: bool) -> Vec<Point2D> {
+    let mut out: Vec<Point2D> = Vec::with_capacity(points.len());
+    for p in points {
+        if out.last().is_none_or(|q| q.distance_to(p) > 1e-9) {
+            out.push(*p);
+        }
+    }
+    if closed && out.len() > 1 && out[0].distance_to(out.last().unwrap()) <= 1e-9 {
+        out.pop();
+    }
+    out
+}
+
+pub(super) fn signed_area(points: &[Point2D]) -> f64 {
+    Polygon2D::new(points.to_vec()).signed_area()
+}
+
+pub(super) fn make_ccw(points: &mut [Point2D]) {
+    if signed_area(points) < 0.0 {
+        points.reverse();
+    }
+}
+
+/// Offset a counter-clockwise loop (positive = outward) with mitered corners
+///
+/// Edges that collapse under the offset are removed and their neighbours
+/// re-intersected, so narrow features shrink away instead of producing
+/// inverted loops. Returns `None` once the whole loop collapses.
+pub(crate) fn offset_loop(points: &[Point2D], distance: f64) -> Option<Vec<Point2D>> {
+    // Each edge as (point on offset line, unit direction, original direction)
+    let mut edges: Vec<(Point2D, (f64, f64))> = Vec::with_capacity(points.len());
+    for i in 0..points.len() {
+        let a = points[i];
+        let b = points[(i + 1) % points.len()];
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let len = (dx * dx + dy * dy).sqrt();
+        if len < 1e-12 {
+            continue;
+        }
+        let (ux, uy) = (dx / len, dy / len);
+        // Right-hand normal points outward on a CCW loop
+        let origin = Point2D::new(a.x + uy * distance, a.y - ux * distance);
+        edges.push((origin, (ux, uy)));
+    }
+
+    loop {
+        // Merge neighbours that ended up on the same line
+        let mut i = 0;
+        while edges.len() > 1 && i < edges.len() {
+            let prev = edges[(i + edges.len() - 1) % edges.len()];
+            let (p, (ux, uy)) = edges[i];
+            let (q, (vx, vy)) = prev;
+            let same_line = ux * vx + uy * vy > 1.0 - 1e-9
+                && ((p.x - q.x) * vy - (p.y - q.y) * vx).abs() < 1e-9;
+            if same_line {
+                edges.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        if edges.len() < 3 {
+            return None;
+        }
+
+        let n = edges.len();
+        let vertices: Vec<Point2D> = (0..n)
+            .map(|i| {
+                let prev = edges[(i + n - 1) % n];
+                let cur = edges[i];
+                line_intersection(prev, cur).unwrap_or(cur.0)
+            })
+            .collect();
+
+        // Find edges whose direction flipped: they have collapsed. Two
+        // neighbours running in opposite directions are the walls of a slot
+        // whose floor already collapsed, so they go too.
+        let collapsed: Vec<usize> = (0..n)
+            .filter(|&i| {
+                let a = vertices[i];
+                let b = vertices[(i + 1) % n];
+                let (ux, uy) = edges[i].1;
+                let antiparallel = |j: usize| {
+                    let (vx, vy) = edges[j].1;
+                    ux * vx + uy * vy < -1.0 + 1e-9
+                };
+                (b.x - a.x) * ux + (b.y - a.y) * uy < -1e-9
+                    || antiparallel((i + n - 1) % n)
+                    || antiparallel((i + 1) % n)
+            })
+            .collect();
+
+        if collapsed.is_empty() {
+            if signed_area(&vertices) <= 1e-12 {
+                return None;
+            }
+            return Some(vertices);
+        }
+        if collapsed.len() == n {
+            return None;
+        }
+        for &i in collapsed.iter().rev() {
+            edges.remove(i);
+        }
+    }
+}
+
+fn line_intersection(a: (Point2D, (f64, f64)), b: (Point2D, (f64, f64))) -> Option<Point2D> {
+    let (p, (dx1, dy1)) = a;
+    let (q, (dx2, dy2)) = b;
+    let denom = dx1 * dy2 - dy1 * dx2;
+    if denom.abs() < 1e-12 {
+        return None;
+    }
+    let t = ((q.x - p.x) * dy2 - (q.y - p.y) * dx2) / denom;
+    Some(Point2D::new(p.x + t * dx1, p.y + t * dy1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(size: f64) -> Vec<Point2D> {
+        vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(size, 0.0),
+            Point2D::new(size, size),
+            Point2D::new(0.0, size),
+        ]
+    }
+
+    #[test]
+    fn test_depth_levels() {
+        let params = CutParameters {
+            final_depth: 2.5,
+            stepdown: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(params.depth_levels(), vec![-1.0, -2.0, -2.5]);
+
+        let exact = CutParameters {
+            final_depth: 2.0,
+            stepdown: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(exact.depth_levels(), vec![-1.0, -2.0]);
+    }
+
+    #[test]
+    fn test_offset_loop_square() {
+        let out = offset_loop(&square(10.0), 1.0).unwrap();
+        assert!((signed_area(&out) - 144.0).abs() < 1e-9);
+
+        let inset = offset_loop(&square(10.0), -2.0).unwrap();
+        assert!((signed_area(&inset) - 36.0).abs() < 1e-9);
+
+        assert!(offset_loop(&square(10.0), -5.5).is_none());
+    }
+
+    #[test]
+    fn test_offset_loop_removes_collapsed_notch() {
+        // 10x10 square with a 1 mm wide, 4 mm deep slot in the top edge
+        let shape = vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(10.0, 0.0),
+            Point2D::new(10.0, 10.0),
+            Point2D::new(5.5, 10.0),
+            Point2D::new(5.5, 6.0),
+            Point2D::new(4.5, 6.0),
+            Point2D::new(4.5, 10.0),
+            Point2D::new(0.0, 10.0),
+        ];
+        // Growing by 1 mm closes the slot entirely
+        let out = offset_loop(&shape, 1.0).unwrap();
+        assert_eq!(out.len(), 4);
+        assert!((signed_area(&out) - 144.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_outside_contour() {
+        let op = ContourOperation::new(
+            "outside",
+            Polyline2D::closed(square(20.0)),
+            ContourSide::Outside,
+        );
+        let tool = Tool::end_mill(1, 6.0);
+        let path = op.generate(&tool).unwrap();
+
+        // Every cutting move at depth is 3 mm (tool radius) outside the part
+        for m in &path.moves {
+            if let Move::Linear { to, .. } = m {
+                if to.z < 0.0 {
+                    let outside_x = to.x <= -3.0 + 1e-9 || to.x >= 23.0 - 1e-9;
+                    let outside_y = to.y <= -3.0 + 1e-9 || to.y >= 23.0 - 1e-9;
+                    assert!(outside_x || outside_y);
+                }
+            }
+        }
+        // Three passes of 26x26 perimeter plus plunges
+        let perimeter = 4.0 * 26.0;
+        assert!((path.cutting_length() - (3.0 * perimeter + 3.0 + 1.0)).abs() < 1e-6);
+        assert_eq!(path.current_position().unwrap().z, op.params.safe_z);
+    }
+
+    #[test]
+    fn test_contour_rejects_bad_input() {
+        let open = ContourOperation::new(
+            "open",
+            Polyline2D::open(vec![Point2D::new(0.0, 0.0), Point2D::new(5.0, 0.0)]),
+            ContourSide::Inside,
+        );
+        assert!(open.generate(&Tool::end_mill(1, 3.0)).is_err());
+
+        let tiny =
+            ContourOperation::new("tiny", Polyline2D::closed(square(4.0)), ContourSide::Inside);
+        assert!(tiny.generate(&Tool::end_mill(1, 6.0)).is_err());
+
+        let drill = ContourOperation::new("d", Polyline2D::closed(square(40.0)), ContourSide::On);
+        assert!(drill.generate(&Tool::drill(2, 6.0)).is_err());
+    }
+
+    #[test]
+    fn test_offset_pocket_stays_inside() {
+        let op = PocketOperation::new(
+            "pocket",
+            Polygon2D::new(square(30.0)),
+            PocketStrategy::Offset,
+        );
+        let tool = Tool::end_mill(1, 6.0);
+        let path = op.generate(&tool).unwrap();
+
+        for m in &path.moves {
+            if let Move::Linear { to, .. } = m {
+                assert!(to.x >= 3.0 - 1e-9 && to.x <= 27.0 + 1e-9);
+                assert!(to.y >= 3.0 - 1e-9 && to.y <= 27.0 + 1e-9);
+            }
+        }
+        assert!(path.cutting_length() > 0.0);
+    }
+
+    #[test]
+    fn test_zigzag_pocket_avoids_island() {
+        let island = vec![
+            Point2D::new(12.0, 12.0),
+            Point2D::new(18.0, 12.0),
+            Point2D::new(18.0, 18.0),
+            Point2D::new(12.0, 18.0),
+        ];
+        let boundary = Polygon2D::with_holes(square(30.0), vec![island]);
+        let op = PocketOperation::new(
+            "zz",
+            boundary.clone(),
+            PocketStrategy::ZigZag { angle: 0.0 },
+        );
+        let tool = Tool::end_mill(1, 4.0);
+        let path = op.generate(&tool).unwrap();
+
+        // No cutting move ends inside the island grown by the tool radius
+        for m in &path.moves {
+            if let Move::Linear { to, .. } = m {
+                let inside = to.x > 10.0 + 1e-6
+                    && to.x < 20.0 - 1e-6
+                    && to.y > 10.0 + 1e-6
+                    && to.y < 20.0 - 1e-6;
+                assert!(!inside, "cut into island at {:?}", to);
+            }
+        }
+
+        let offset = PocketOperation::new("off", boundary, PocketStrategy::Offset);
+        assert!(matches!(
+            offset.generate(&tool),
+            Err(CamError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_drill_order_and_cycles() {
+        let holes = vec![
+            Point2D::new(50.0, 0.0),
+            Point2D::new(10.0, 0.0),
+            Point2D::new(30.0, 0.0),
+        ];
+        let mut op = DrillOperation::new("holes", holes, 8.0);
+        op.cycle = DrillCycleKind::Peck(2.0);
+        let path = op.generate(&Tool::drill(3, 5.0)).unwrap();
+
+        let xs: Vec<f64> = path
+            .moves
+            .iter()
+            .filter_map(|m| match m {
+                Move::Drill(c) => {
+                    assert_eq!(c.peck, Some(2.0));
+                    assert_eq!(c.bottom_z, -8.0);
+                    Some(c.x)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(xs, vec![10.0, 30.0, 50.0]);
+
+        op.cycle = DrillCycleKind::Peck(0.0);
+        assert!(op.generate(&Tool::drill(3, 5.0)).is_err());
+    }
+}
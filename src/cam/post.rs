@@ -0,0 +1,463 @@
+//! G-code post-processing
+//!
+//! Turns toolpaths into G-code for a specific controller. Dialects differ in
+//! program framing, tool change handling, canned drilling cycle support, and
+//! dwell units; everything else is plain RS-274.
+
+use serde::{Deserialize, Serialize};
+
+use super::toolpath::{DrillCycle, Move, Toolpath};
+use super::{CamError, CamResult};
+use crate::core::Point3;
+
+/// Target controller dialect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Controller {
+    /// Grbl (hobby routers); no tool changer, no canned cycles
+    Grbl,
+    /// LinuxCNC
+    LinuxCnc,
+    /// Mach3 / Mach4
+    Mach3,
+    /// Fanuc-compatible industrial controls
+    Fanuc,
+}
+
+impl Controller {
+    /// Display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Controller::Grbl => "Grbl",
+            Controller::LinuxCnc => "LinuxCNC",
+            Controller::Mach3 => "Mach3",
+            Controller::Fanuc => "Fanuc",
+        }
+    }
+
+    /// Whether G81/G82/G83 are available
+    pub fn supports_canned_cycles(&self) -> bool {
+        !matches!(self, Controller::Grbl)
+    }
+
+    /// Whether M06 performs an automatic tool change
+    pub fn supports_tool_change(&self) -> bool {
+        !matches!(self, Controller::Grbl)
+    }
+
+    /// Whether the controller applies tool length offsets with G43
+    fn uses_length_offset(&self) -> bool {
+        matches!(self, Controller::LinuxCnc | Controller::Fanuc)
+    }
+}
+
+/// G-code post-processor settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostProcessor {
+    /// Target controller
+    pub controller: Controller,
+    /// Digits after the decimal point for coordinates
+    pub decimals: usize,
+    /// Emit N line numbers (always on for Fanuc)
+    pub line_numbers: bool,
+    /// Program number for controllers that use O-words
+    pub program_number: u32,
+    /// Turn flood coolant on while cutting
+    pub coolant: bool,
+}
+
+impl PostProcessor {
+    /// Create a post-processor with the controller's usual defaults
+    pub fn new(controller: Controller) -> Self {
+        Self {
+            controller,
+            decimals: 3,
+            line_numbers: controller == Controller::Fanuc,
+            program_number: 1,
+            coolant: false,
+        }
+    }
+
+    /// Post a sequence of toolpaths as one program
+    pub fn post(&self, toolpaths: &[Toolpath]) -> CamResult<String> {
+        if toolpaths.is_empty() {
+            return Err(CamError::InvalidParameters("nothing to post".into()));
+        }
+
+        let mut out = Writer::new(self);
+        self.header(&mut out);
+
+        let mut current_tool: Option<u32> = None;
+        for path in toolpaths {
+            out.comment(&path.name);
+            if current_tool != Some(path.tool.number) {
+                self.tool_change(&mut out, path, current_tool.is_none());
+                current_tool = Some(path.tool.number);
+            }
+            self.toolpath(&mut out, path);
+        }
+
+        self.footer(&mut out);
+        Ok(out.finish())
+    }
+
+    pub(super) fn header(&self, out: &mut Writer) {
+        if self.controller == Controller::Fanuc {
+            out.raw("%");
+            out.raw(&format!("O{:04}", self.program_number));
+        }
+        out.comment(&format!("Generated by CADDY {}", crate::VERSION));
+        out.comment(&format!("Controller: {}", self.controller.name()));
+        out.line("G21");
+        out.line("G90 G17 G94");
+        if self.controller != Controller::Grbl {
+            out.line("G40 G49 G80");
+        }
+    }
+
+    fn tool_change(&self, out: &mut Writer, path: &Toolpath, first: bool) {
+        let tool = &path.tool;
+        if self.controller.supports_tool_change() {
+            if !first {
+                out.line("M05");
+            }
+            out.comment(&format!("T{} {}", tool.number, tool.name));
+            out.line(&format!("T{} M06", tool.number));
+            if self.controller.uses_length_offset() {
+                out.line(&format!("G43 H{}", tool.number));
+            }
+        } else {
+            // Manual change: stop the spindle and pause for the operator
+            out.comment(&format!("Load T{} {}", tool.number, tool.name));
+            if !first {
+                out.line("M05");
+                out.line("M00");
+            }
+        }
+        out.line(&format!("S{:.0} M03", path.spindle_speed));
+        if self.coolant {
+            out.line("M08");
+        }
+        out.feed = None;
+    }
+
+    fn toolpath(&self, out: &mut Writer, path: &Toolpath) {
+        let mut in_cycle = false;
+        for m in &path.moves {
+            match m {
+                Move::Rapid(p) => {
+                    out.end_cycle(&mut in_cycle);
+                    out.motion("G0", p, None);
+                }
+                Move::Linear { to, feed } => {
+                    out.end_cycle(&mut in_cycle);
+                    out.motion("G1", to, Some(*feed));
+                }
+                Move::Drill(cycle) => {
+                    if self.controller.supports_canned_cycles() {
+                        self.canned_cycle(out, cycle, &mut in_cycle);
+                    } else {
+                        self.expanded_cycle(out, cycle);
+                    }
+                }
+            }
+        }
+        out.end_cycle(&mut in_cycle);
+    }
+
+    fn canned_cycle(&self, out: &mut Writer, c: &DrillCycle, in_cycle: &mut bool) {
+        let code = match (c.peck, c.dwell) {
+            (Some(_), _) => "G83",
+            (None, Some(_)) => "G82",
+            (None, None) => "G81",
+        };
+        let mut words = format!(
+            "G98 {} X{} Y{} Z{} R{}",
+            code,
+            out.num(c.x),
+            out.num(c.y),
+            out.num(c.bottom_z),
+            out.num(c.retract_z)
+        );
+        if let Some(peck) = c.peck {
+            words.push_str(&format!(" Q{}", out.num(peck)));
+        }
+        if let Some(dwell) = c.dwell {
+            words.push_str(&format!(" P{}", self.dwell_word(dwell)));
+        }
+        words.push_str(&format!(" F{}", out.num(c.feed)));
+        out.line(&words);
+        out.feed = Some(c.feed);
+        // G98 returns to the initial Z between holes
+        let z = out.position.map_or(c.retract_z, |p| p.z.max(c.retract_z));
+        out.position = Some(Point3::new(c.x, c.y, z));
+        *in_cycle = true;
+    }
+
+    fn expanded_cycle(&self, out: &mut Writer, c: &DrillCycle) {
+        let start_z = out.position.map_or(c.retract_z, |p| p.z.max(c.retract_z));
+        out.motion("G0", &Point3::new(c.x, c.y, start_z), None);
+        out.motion("G0", &Point3::new(c.x, c.y, c.retract_z), None);
+
+        match c.peck {
+            Some(peck) => {
+                let mut depth = c.retract_z;
+                while depth > c.bottom_z + 1e-9 {
+                    let clearance = (depth + 0.5).min(c.retract_z);
+                    if depth < c.retract_z {
+                        out.motion("G0", &Point3::new(c.x, c.y, clearance), None);
+                    }
+                    depth = (depth - peck).max(c.bottom_z);
+                    out.motion("G1", &Point3::new(c.x, c.y, depth), Some(c.feed));
+                    out.motion("G0", &Point3::new(c.x, c.y, c.retract_z), None);
+                }
+            }
+            None => {
+                out.motion("G1", &Point3::new(c.x, c.y, c.bottom_z), Some(c.feed));
+                if let Some(dwell) = c.dwell {
+                    out.line(&format!("G4 P{}", self.dwell_word(dwell)));
+                }
+                out.motion("G0", &Point3::new(c.x, c.y, c.retract_z), None);
+            }
+        }
+        if start_z > c.retract_z {
+            out.motion("G0", &Point3::new(c.x, c.y, start_z), None);
+        }
+    }
+
+    /// Dwell word value; Fanuc takes milliseconds, the others seconds
+    pub(super) fn dwell_word(&self, seconds: f64) -> String {
+        match self.controller {
+            Controller::Fanuc => format!("{:.0}", seconds * 1000.0),
+            _ => format!("{:.2}", seconds),
+        }
+    }
+
+    pub(super) fn footer(&self, out: &mut Writer) {
+        out.line("M05");
+        if self.coolant {
+            out.line("M09");
+        }
+        if self.controller == Controller::Fanuc {
+            out.line("G91 G28 Z0");
+            out.line("G90");
+        }
+        out.line("M30");
+        if self.controller == Controller::Fanuc {
+            out.raw("%");
+        }
+    }
+}
+
+/// Line buffer tracking modal state
+pub(super) struct Writer {
+    lines: Vec<String>,
+    decimals: usize,
+    line_numbers: bool,
+    uppercase_comments: bool,
+    next_number: u32,
+    feed: Option<f64>,
+    position: Option<Point3>,
+}
+
+impl Writer {
+    pub(super) fn new(post: &PostProcessor) -> Self {
+        Self {
+            lines: Vec::new(),
+            decimals: post.decimals,
+            line_numbers: post.line_numbers,
+            uppercase_comments: post.controller == Controller::Fanuc,
+            next_number: 10,
+            feed: None,
+            position: None,
+        }
+    }
+
+    pub(super) fn raw(&mut self, text: &str) {
+        self.lines.push(text.to_string());
+    }
+
+    pub(super) fn line(&mut self, text: &str) {
+        if self.line_numbers {
+            self.lines.push(format!("N{} {}", self.next_number, text));
+            self.next_number += 10;
+        } else {
+            self.lines.push(text.to_string());
+        }
+    }
+
+    pub(super) fn comment(&mut self, text: &str) {
+        // Parentheses would terminate the comment early
+        let clean: String = text
+            .chars()
+            .map(|c| if c == '(' || c == ')' { ' ' } else { c })
+            .collect();
+        let clean = if self.uppercase_comments {
+            clean.to_uppercase()
+        } else {
+            clean
+        };
+        self.raw(&format!("({})", clean));
+    }
+
+    pub(super) fn num(&self, value: f64) -> String {
+        let s = format!("{:.*}", self.decimals, value);
+        // Avoid "-0.000"
+        if s.starts_with('-') && s[1..].chars().all(|c| c == '0' || c == '.') {
+            s[1..].to_string()
+        } else {
+            s
+        }
+    }
+
+    /// Emit a motion word, omitting axes that did not change or are NaN
+    pub(super) fn motion(&mut self, code: &str, to: &Point3, feed: Option<f64>) {
+        let mut words = code.to_string();
+        let prev = self.position;
+        for (axis, value, old) in [
+            ('X', to.x, prev.map(|p| p.x)),
+            ('Y', to.y, prev.map(|p| p.y)),
+            ('Z', to.z, prev.map(|p| p.z)),
+        ] {
+            if value.is_nan() {
+                continue;
+            }
+            let text = self.num(value);
+            if old.is_none_or(|o| self.num(o) != text) {
+                words.push(' ');
+                words.push(axis);
+                words.push_str(&text);
+            }
+        }
+        if words == code {
+            return;
+        }
+        if let Some(f) = feed {
+            if self.feed != Some(f) {
+                words.push_str(&format!(" F{}", self.num(f)));
+                self.feed = Some(f);
+            }
+        }
+        self.line(&words);
+        let keep = |value: f64, old: Option<f64>| {
+            if value.is_nan() {
+                old.unwrap_or(f64::NAN)
+            } else {
+                value
+            }
+        };
+        self.position = Some(Point3::new(
+            keep(to.x, prev.map(|p| p.x)),
+            keep(to.y, prev.map(|p| p.y)),
+            keep(to.z, prev.map(|p| p.z)),
+        ));
+    }
+
+    fn end_cycle(&mut self, in_cycle: &mut bool) {
+        if *in_cycle {
+            self.line("G80");
+            *in_cycle = false;
+        }
+    }
+
+    pub(super) fn finish(self) -> String {
+        let mut text = self.lines.join("\n");
+        text.push('\n');
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cam::tool::Tool;
+
+    fn square_path() -> Toolpath {
+        let mut tp = Toolpath::new("Profile (outside)", Tool::end_mill(1, 6.0), 5.0);
+        tp.rapid(0.0, 0.0, 5.0);
+        tp.feed(0.0, 0.0, -1.0, 200.0);
+        tp.feed(10.0, 0.0, -1.0, 600.0);
+        tp.feed(10.0, 10.0, -1.0, 600.0);
+        tp.rapid(10.0, 10.0, 5.0);
+        tp
+    }
+
+    fn drill_path() -> Toolpath {
+        let mut tp = Toolpath::new("Holes", Tool::drill(2, 5.0), 5.0);
+        tp.rapid(20.0, 20.0, 5.0);
+        tp.moves.push(Move::Drill(DrillCycle {
+            x: 20.0,
+            y: 20.0,
+            bottom_z: -4.0,
+            retract_z: 1.0,
+            peck: Some(2.0),
+            dwell: None,
+            feed: 100.0,
+        }));
+        tp.rapid(20.0, 20.0, 5.0);
+        tp
+    }
+
+    #[test]
+    fn test_linuxcnc_program() {
+        let gcode = PostProcessor::new(Controller::LinuxCnc)
+            .post(&[square_path(), drill_path()])
+            .unwrap();
+        let lines: Vec<&str> = gcode.lines().collect();
+
+        assert!(lines.contains(&"G21"));
+        assert!(lines.contains(&"T1 M06"));
+        assert!(lines.contains(&"G43 H1"));
+        assert!(lines.contains(&"G0 X0.000 Y0.000 Z5.000"));
+        assert!(lines.contains(&"G1 Z-1.000 F200.000"));
+        // Feed is modal and unchanged axes are omitted
+        assert!(lines.contains(&"G1 X10.000 F600.000"));
+        assert!(lines.contains(&"G1 Y10.000"));
+        assert!(lines.contains(&"(Profile  outside )"));
+        assert!(lines
+            .iter()
+            .any(|l| l.starts_with("G98 G83 X20.000 Y20.000 Z-4.000 R1.000 Q2.000")));
+        assert!(lines.contains(&"G80"));
+        assert_eq!(lines.last(), Some(&"M30"));
+    }
+
+    #[test]
+    fn test_grbl_expands_drill_cycles() {
+        let gcode = PostProcessor::new(Controller::Grbl)
+            .post(&[square_path(), drill_path()])
+            .unwrap();
+
+        assert!(!gcode.contains("G83"));
+        assert!(!gcode.contains("M06"));
+        assert!(gcode.contains("M00"));
+        // Two pecks: 1 -> -1, then -1 -> -3 ... down to -4
+        assert!(gcode.contains("G1 Z-1.000 F100.000"));
+        assert!(gcode.contains("G1 Z-4.000"));
+    }
+
+    #[test]
+    fn test_fanuc_framing() {
+        let mut post = PostProcessor::new(Controller::Fanuc);
+        post.program_number = 42;
+        let mut path = drill_path();
+        if let Move::Drill(c) = &mut path.moves[1] {
+            c.peck = None;
+            c.dwell = Some(0.5);
+        }
+        let gcode = post.post(&[path]).unwrap();
+        let lines: Vec<&str> = gcode.lines().collect();
+
+        assert_eq!(lines[0], "%");
+        assert_eq!(lines[1], "O0042");
+        assert_eq!(lines.last(), Some(&"%"));
+        assert!(lines.iter().any(|l| l.starts_with("N10 ")));
+        assert!(lines
+            .iter()
+            .any(|l| l.contains("G82") && l.contains("P500")));
+        assert!(lines.contains(&"(HOLES)"));
+    }
+
+    #[test]
+    fn test_empty_program_rejected() {
+        assert!(PostProcessor::new(Controller::Mach3).post(&[]).is_err());
+    }
+}
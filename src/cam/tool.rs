@@ -0,0 +1,202 @@
+//! Cutting tool definitions
+//!
+//! Tools carry the geometry needed for offsetting (diameter) and the default
+//! cutting data (feeds and speeds) used when an operation does not override it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{CamError, CamResult};
+
+/// Tool shape
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ToolKind {
+    /// Flat end mill
+    FlatEndMill,
+    /// Ball nose end mill
+    BallEndMill,
+    /// Bull nose end mill with a corner radius
+    BullNoseEndMill {
+        /// Corner radius
+        corner_radius: f64,
+    },
+    /// V-bit / engraving cutter
+    VBit {
+        /// Included angle in degrees
+        angle: f64,
+    },
+    /// Twist drill
+    Drill {
+        /// Point angle in degrees (118 or 135 typically)
+        point_angle: f64,
+    },
+}
+
+impl ToolKind {
+    /// Whether the tool can cut sideways (mill) or only plunge (drill)
+    pub fn can_side_cut(&self) -> bool {
+        !matches!(self, ToolKind::Drill { .. })
+    }
+}
+
+/// A cutting tool with default cutting data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tool {
+    /// Tool number in the machine's tool table
+    pub number: u32,
+    /// Display name
+    pub name: String,
+    /// Tool shape
+    pub kind: ToolKind,
+    /// Cutting diameter
+    pub diameter: f64,
+    /// Maximum cutting depth (flute length)
+    pub flute_length: f64,
+    /// Default feed rate (units/min)
+    pub feed_rate: f64,
+    /// Default plunge rate (units/min)
+    pub plunge_rate: f64,
+    /// Spindle speed (RPM)
+    pub spindle_speed: f64,
+}
+
+impl Tool {
+    /// Create a flat end mill with conservative defaults
+    pub fn end_mill(number: u32, diameter: f64) -> Self {
+        Self {
+            number,
+            name: format!("{} mm flat end mill", diameter),
+            kind: ToolKind::FlatEndMill,
+            diameter,
+            flute_length: diameter * 3.0,
+            feed_rate: 600.0,
+            plunge_rate: 200.0,
+            spindle_speed: 12000.0,
+        }
+    }
+
+    /// Create a twist drill with conservative defaults
+    pub fn drill(number: u32, diameter: f64) -> Self {
+        Self {
+            number,
+            name: format!("{} mm drill", diameter),
+            kind: ToolKind::Drill { point_angle: 118.0 },
+            diameter,
+            flute_length: diameter * 8.0,
+            feed_rate: 150.0,
+            plunge_rate: 150.0,
+            spindle_speed: 3000.0,
+        }
+    }
+
+    /// Set the feed and plunge rates
+    pub fn with_feeds(mut self, feed_rate: f64, plunge_rate: f64) -> Self {
+        self.feed_rate = feed_rate;
+        self.plunge_rate = plunge_rate;
+        self
+    }
+
+    /// Set the spindle speed
+    pub fn with_spindle_speed(mut self, rpm: f64) -> Self {
+        self.spindle_speed = rpm;
+        self
+    }
+
+    /// Tool radius
+    pub fn radius(&self) -> f64 {
+        self.diameter / 2.0
+    }
+
+    /// Check that the tool definition is usable
+    pub fn validate(&self) -> CamResult<()> {
+        if self.diameter.is_nan() || self.diameter <= 0.0 {
+            return Err(CamError::InvalidTool(format!(
+                "T{}: diameter must be positive",
+                self.number
+            )));
+        }
+        if self.feed_rate.is_nan()
+            || self.feed_rate <= 0.0
+            || self.plunge_rate.is_nan()
+            || self.plunge_rate <= 0.0
+        {
+            return Err(CamError::InvalidTool(format!(
+                "T{}: feed and plunge rates must be positive",
+                self.number
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Tool table keyed by tool number
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolLibrary {
+    tools: BTreeMap<u32, Tool>,
+}
+
+impl ToolLibrary {
+    /// Create an empty library
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace a tool
+    pub fn add(&mut self, tool: Tool) -> CamResult<()> {
+        tool.validate()?;
+        self.tools.insert(tool.number, tool);
+        Ok(())
+    }
+
+    /// Look up a tool by number
+    pub fn get(&self, number: u32) -> Option<&Tool> {
+        self.tools.get(&number)
+    }
+
+    /// Remove a tool
+    pub fn remove(&mut self, number: u32) -> Option<Tool> {
+        self.tools.remove(&number)
+    }
+
+    /// All tools in tool-number order
+    pub fn tools(&self) -> impl Iterator<Item = &Tool> {
+        self.tools.values()
+    }
+
+    /// Number of tools
+    pub fn len(&self) -> usize {
+        self.tools.len()
+    }
+
+    /// Whether the library is empty
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_validation() {
+        assert!(Tool::end_mill(1, 6.0).validate().is_ok());
+        assert!(Tool::end_mill(1, 0.0).validate().is_err());
+        assert!(Tool::end_mill(1, 6.0)
+            .with_feeds(0.0, 100.0)
+            .validate()
+            .is_err());
+        assert!(!Tool::drill(2, 5.0).kind.can_side_cut());
+    }
+
+    #[test]
+    fn test_library_ordering() {
+        let mut lib = ToolLibrary::new();
+        lib.add(Tool::drill(5, 3.0)).unwrap();
+        lib.add(Tool::end_mill(1, 6.0)).unwrap();
+        let numbers: Vec<u32> = lib.tools().map(|t| t.number).collect();
+        assert_eq!(numbers, vec![1, 5]);
+        assert!(lib.add(Tool::end_mill(2, -1.0)).is_err());
+        assert_eq!(lib.len(), 2);
+    }
+}
@@ -0,0 +1,181 @@
+//! Machine-independent toolpaths
+//!
+//! A toolpath is an ordered list of moves in work coordinates (Z = 0 at the
+//! stock top, cutting depths negative). Post-processors turn toolpaths into
+//! controller-specific G-code.
+
+use crate::core::Point3;
+use serde::{Deserialize, Serialize};
+
+use super::tool::Tool;
+
+/// Canned drilling cycle parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DrillCycle {
+    /// Hole center X
+    pub x: f64,
+    /// Hole center Y
+    pub y: f64,
+    /// Bottom of the hole
+    pub bottom_z: f64,
+    /// Retract plane for the cycle (R plane)
+    pub retract_z: f64,
+    /// Peck increment; `None` for a single plunge
+    pub peck: Option<f64>,
+    /// Dwell at the bottom in seconds
+    pub dwell: Option<f64>,
+    /// Plunge feed rate
+    pub feed: f64,
+}
+
+/// Single toolpath move
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Move {
+    /// Rapid positioning (G0)
+    Rapid(Point3),
+    /// Linear cutting move (G1) at a feed rate
+    Linear {
+        /// Target position
+        to: Point3,
+        /// Feed rate (units/min)
+        feed: f64,
+    },
+    /// Drilling cycle at one hole
+    Drill(DrillCycle),
+}
+
+impl Move {
+    /// End position of the move
+    pub fn end_point(&self) -> Point3 {
+        match self {
+            Move::Rapid(p) => *p,
+            Move::Linear { to, .. } => *to,
+            Move::Drill(c) => Point3::new(c.x, c.y, c.retract_z),
+        }
+    }
+}
+
+/// Ordered sequence of moves for one tool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Toolpath {
+    /// Operation name, emitted as a comment
+    pub name: String,
+    /// Tool used for every move
+    pub tool: Tool,
+    /// Spindle speed (RPM)
+    pub spindle_speed: f64,
+    /// Clearance height used for rapids between features
+    pub safe_z: f64,
+    /// Moves in order
+    pub moves: Vec<Move>,
+}
+
+impl Toolpath {
+    /// Create an empty toolpath
+    pub fn new(name: impl Into<String>, tool: Tool, safe_z: f64) -> Self {
+        let spindle_speed = tool.spindle_speed;
+        Self {
+            name: name.into(),
+            tool,
+            spindle_speed,
+            safe_z,
+            moves: Vec::new(),
+        }
+    }
+
+    /// Append a rapid move
+    pub fn rapid(&mut self, x: f64, y: f64, z: f64) {
+        self.moves.push(Move::Rapid(Point3::new(x, y, z)));
+    }
+
+    /// Append a linear feed move
+    pub fn feed(&mut self, x: f64, y: f64, z: f64, feed: f64) {
+        self.moves.push(Move::Linear {
+            to: Point3::new(x, y, z),
+            feed,
+        });
+    }
+
+    /// Last commanded position, if any
+    pub fn current_position(&self) -> Option<Point3> {
+        self.moves.last().map(|m| m.end_point())
+    }
+
+    /// Total length of cutting moves
+    pub fn cutting_length(&self) -> f64 {
+        self.lengths().0
+    }
+
+    /// Total length of rapid moves
+    pub fn rapid_length(&self) -> f64 {
+        self.lengths().1
+    }
+
+    /// Estimated machining time in minutes given the machine's rapid rate
+    pub fn estimated_minutes(&self, rapid_rate: f64) -> f64 {
+        let mut minutes = 0.0;
+        let mut prev: Option<Point3> = None;
+        for m in &self.moves {
+            let from = prev.unwrap_or_else(|| m.end_point());
+            match m {
+                Move::Rapid(to) => minutes += (to - from).norm() / rapid_rate,
+                Move::Linear { to, feed } => minutes += (to - from).norm() / feed,
+                Move::Drill(c) => {
+                    let depth = c.retract_z - c.bottom_z;
+                    let plunge = match c.peck {
+                        // Each peck retracts to the R plane and comes back
+                        Some(peck) if peck > 0.0 => {
+                            let pecks = (depth / peck).ceil();
+                            depth + pecks * depth
+                        }
+                        _ => depth,
+                    };
+                    minutes += (Point3::new(c.x, c.y, from.z) - from).norm() / rapid_rate;
+                    minutes += plunge / c.feed + depth / rapid_rate;
+                    minutes += c.dwell.unwrap_or(0.0) / 60.0;
+                }
+            }
+            prev = Some(m.end_point());
+        }
+        minutes
+    }
+
+    fn lengths(&self) -> (f64, f64) {
+        let mut cutting = 0.0;
+        let mut rapid = 0.0;
+        let mut prev: Option<Point3> = None;
+        for m in &self.moves {
+            if let Some(from) = prev {
+                match m {
+                    Move::Rapid(to) => rapid += (to - from).norm(),
+                    Move::Linear { to, .. } => cutting += (to - from).norm(),
+                    Move::Drill(c) => {
+                        rapid += (Point3::new(c.x, c.y, from.z) - from).norm();
+                        cutting += c.retract_z - c.bottom_z;
+                    }
+                }
+            }
+            prev = Some(m.end_point());
+        }
+        (cutting, rapid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lengths_and_time() {
+        let mut tp = Toolpath::new("square", Tool::end_mill(1, 6.0), 5.0);
+        tp.rapid(0.0, 0.0, 5.0);
+        tp.feed(0.0, 0.0, -1.0, 100.0);
+        tp.feed(10.0, 0.0, -1.0, 100.0);
+        tp.rapid(10.0, 0.0, 5.0);
+
+        assert!((tp.cutting_length() - 16.0).abs() < 1e-9);
+        assert!((tp.rapid_length() - 6.0).abs() < 1e-9);
+        // 16 mm at 100 mm/min plus 6 mm at 1000 mm/min
+        assert!((tp.estimated_minutes(1000.0) - 0.166).abs() < 1e-9);
+    }
+}
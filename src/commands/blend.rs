@@ -0,0 +1,430 @@
+// Edge blend commands for CADDY CAD system
+// Fillets and chamfers edges of a solid stored in the document, with undo/redo
+
+use super::command::*;
+use crate::engine3d::blend::{
+    edge_chain, variable_fillet_chain, BlendOperation, EdgeBlend, DEFAULT_CHAIN_TURN,
+};
+use crate::engine3d::mesh::{EdgeHandle, HalfEdgeMesh};
+use std::any::Any;
+
+// ==================== SHARED SOLID EDIT ====================
+
+/// Solid a blend command changes, with the meshes swapped on undo and redo
+#[derive(Clone, Default)]
+struct SolidEdit {
+    solid: Option<EntityId>,
+    before: Option<HalfEdgeMesh>,
+    after: Option<HalfEdgeMesh>,
+}
+
+impl SolidEdit {
+    fn mesh<'a>(context: &'a CommandContext, id: &EntityId) -> Option<&'a HalfEdgeMesh> {
+        context.document.get_entity(id)?.downcast_ref::<HalfEdgeMesh>()
+    }
+
+    /// The solid given, the `solid=<id>` option, or the first selected solid
+    fn resolve(&self, context: &CommandContext) -> CommandResult<EntityId> {
+        let id = match (self.solid, context.get_option("solid")) {
+            (Some(id), _) => id,
+            (None, Some(value)) => value
+                .parse()
+                .map(EntityId::new)
+                .map_err(|_| CommandError::InvalidInput(format!("Invalid solid: {}", value)))?,
+            (None, None) => {
+                return context
+                    .selection
+                    .entities
+                    .iter()
+                    .copied()
+                    .find(|id| Self::mesh(context, id).is_some())
+                    .ok_or_else(|| CommandError::InvalidSelection("Select a solid to blend".to_string()));
+            }
+        };
+        match Self::mesh(context, &id) {
+            Some(_) => Ok(id),
+            None => Err(CommandError::EntityNotFound(format!("No solid {}", id.0))),
+        }
+    }
+
+    /// Blend the solid, keeping the original for undo
+    fn execute(
+        &mut self,
+        context: &mut CommandContext,
+        operation: Option<&BlendOperation>,
+        from_options: fn(&CommandContext, &HalfEdgeMesh) -> CommandResult<BlendOperation>,
+    ) -> CommandResult {
+        let solid = self.resolve(context)?;
+        let mesh = Self::mesh(context, &solid)
+            .ok_or_else(|| CommandError::EntityNotFound(format!("No solid {}", solid.0)))?;
+        let operation = match operation {
+            Some(operation) => operation.clone(),
+            None => from_options(context, mesh)?,
+        };
+        let blended = operation.apply(mesh).map_err(|e| CommandError::GeometricError(e.to_string()))?;
+
+        self.before = Some(mesh.clone());
+        self.after = None;
+        self.solid = Some(solid);
+        context.document.insert_entity(solid, Box::new(blended));
+        Ok(())
+    }
+
+    fn undo(&mut self, context: &mut CommandContext) -> CommandResult {
+        let (Some(solid), Some(before)) = (self.solid, self.before.take()) else {
+            return Err(CommandError::InvalidState("Blend has not been applied".to_string()));
+        };
+        self.after = Self::mesh(context, &solid).cloned();
+        context.document.insert_entity(solid, Box::new(before));
+        Ok(())
+    }
+
+    /// Put the blended solid back; false when there is none to restore
+    fn redo(&mut self, context: &mut CommandContext) -> bool {
+        let (Some(solid), Some(after)) = (self.solid, self.after.take()) else {
+            return false;
+        };
+        self.before = Self::mesh(context, &solid).cloned();
+        context.document.insert_entity(solid, Box::new(after));
+        true
+    }
+}
+
+/// Edges from the `edges=<id,id,...>` option, extended along their tangent
+/// chains with `chain=on`
+fn edges_from_options(context: &CommandContext, mesh: &HalfEdgeMesh) -> CommandResult<Vec<Vec<EdgeHandle>>> {
+    let value = context
+        .get_option("edges")
+        .ok_or_else(|| CommandError::InvalidInput("No edges specified".to_string()))?;
+    let edges = value
+        .split(',')
+        .map(|id| {
+            id.trim()
+                .trim_start_matches('#')
+                .parse()
+                .map(EdgeHandle)
+                .map_err(|_| CommandError::InvalidInput(format!("Invalid edge: {}", id)))
+        })
+        .collect::<CommandResult<Vec<_>>>()?;
+
+    let chain = context.get_option("chain").is_some_and(|v| matches!(v.as_str(), "on" | "yes" | "true"));
+    if !chain {
+        return Ok(edges.into_iter().map(|e| vec![e]).collect());
+    }
+    let mut chains: Vec<Vec<EdgeHandle>> = Vec::new();
+    for edge in edges {
+        if chains.iter().any(|chain| chain.contains(&edge)) {
+            continue;
+        }
+        let chain = edge_chain(mesh, edge, DEFAULT_CHAIN_TURN);
+        chains.push(chain.map_err(|e| CommandError::GeometricError(e.to_string()))?);
+    }
+    Ok(chains)
+}
+
+/// A size given as `<value>` or `<first>:<second>`
+fn size_pair(context: &CommandContext, key: &str) -> CommandResult<Option<(f64, f64)>> {
+    let Some(value) = context.get_option(key) else {
+        return Ok(None);
+    };
+    let parse = |text: &str| {
+        text.trim()
+            .parse::<f64>()
+            .map_err(|_| CommandError::InvalidInput(format!("Invalid {}: {}", key, value)))
+    };
+    let (first, second) = match value.split_once(':') {
+        Some((first, second)) => (parse(first)?, parse(second)?),
+        None => {
+            let size = parse(value)?;
+            (size, size)
+        }
+    };
+    Ok(Some((first, second)))
+}
+
+// ==================== FILLET EDGE COMMAND ====================
+
+/// Round edges of a solid
+///
+/// The solid is a [`HalfEdgeMesh`] entity: the one given, the `solid=<id>`
+/// option or the first selected. From the command line the edges are
+/// `edges=<id,...>`, extended to their tangent chains with `chain=on`, with
+/// `radius=<r>` or a radius varying along each edge or chain as
+/// `radius=<start>:<end>`, and `segments=<n>` facets across.
+#[derive(Clone)]
+pub struct FilletEdgeCommand {
+    operation: Option<BlendOperation>,
+    edit: SolidEdit,
+    state: CommandState,
+}
+
+impl FilletEdgeCommand {
+    pub fn new() -> Self {
+        Self {
+            operation: None,
+            edit: SolidEdit::default(),
+            state: CommandState::AwaitingInput,
+        }
+    }
+
+    /// Command applying `operation` to the solid stored as entity `solid`
+    pub fn with_operation(solid: EntityId, operation: BlendOperation) -> Self {
+        Self {
+            operation: Some(operation),
+            edit: SolidEdit { solid: Some(solid), ..SolidEdit::default() },
+            ..Self::new()
+        }
+    }
+
+    fn operation_from_options(
+        context: &CommandContext,
+        mesh: &HalfEdgeMesh,
+    ) -> CommandResult<BlendOperation> {
+        let (start, end) = size_pair(context, "radius")?
+            .ok_or_else(|| CommandError::InvalidInput("No fillet radius specified".to_string()))?;
+        let mut operation = BlendOperation::new();
+        if let Some(segments) = context.get_option("segments") {
+            let segments = segments
+                .parse()
+                .map_err(|_| CommandError::InvalidInput(format!("Invalid segments: {}", segments)))?;
+            operation = operation.with_segments(segments);
+        }
+        for chain in edges_from_options(context, mesh)? {
+            let blends = variable_fillet_chain(mesh, &chain, start, end)
+                .map_err(|e| CommandError::GeometricError(e.to_string()))?;
+            operation = operation.with_edges(blends);
+        }
+        Ok(operation)
+    }
+}
+
+impl Default for FilletEdgeCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Command for FilletEdgeCommand {
+    fn name(&self) -> &str {
+        "FILLETEDGE"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["FE"]
+    }
+
+    fn description(&self) -> &str {
+        "Round edges of a solid with a constant or varying radius"
+    }
+
+    fn usage(&self) -> &str {
+        "FILLETEDGE edges=<id,...> radius=<r|start:end> [chain=on] [segments=<n>] [solid=<id>]"
+    }
+
+    fn execute(&mut self, context: &mut CommandContext) -> CommandResult {
+        let result = self.edit.execute(context, self.operation.as_ref(), Self::operation_from_options);
+        self.state = match &result {
+            Ok(()) => CommandState::Completed,
+            Err(error) => CommandState::Failed(error.to_string()),
+        };
+        result
+    }
+
+    fn undo(&mut self, context: &mut CommandContext) -> CommandResult {
+        self.edit.undo(context)?;
+        self.state = CommandState::AwaitingInput;
+        Ok(())
+    }
+
+    fn redo(&mut self, context: &mut CommandContext) -> CommandResult {
+        if !self.edit.redo(context) {
+            return self.execute(context);
+        }
+        self.state = CommandState::Completed;
+        Ok(())
+    }
+
+    fn state(&self) -> CommandState {
+        self.state.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// ==================== CHAMFER EDGE COMMAND ====================
+
+/// Bevel edges of a solid
+///
+/// Resolves the solid and edges as [`FilletEdgeCommand`] does. The bevel is
+/// `distance=<d>`, or `distance=<first>:<second>` along the face left of
+/// each edge and the other, or `distance=<d> angle=<degrees>` to the first
+/// face.
+#[derive(Clone)]
+pub struct ChamferEdgeCommand {
+    operation: Option<BlendOperation>,
+    edit: SolidEdit,
+    state: CommandState,
+}
+
+impl ChamferEdgeCommand {
+    pub fn new() -> Self {
+        Self {
+            operation: None,
+            edit: SolidEdit::default(),
+            state: CommandState::AwaitingInput,
+        }
+    }
+
+    /// Command applying `operation` to the solid stored as entity `solid`
+    pub fn with_operation(solid: EntityId, operation: BlendOperation) -> Self {
+        Self {
+            operation: Some(operation),
+            edit: SolidEdit { solid: Some(solid), ..SolidEdit::default() },
+            ..Self::new()
+        }
+    }
+
+    fn operation_from_options(
+        context: &CommandContext,
+        mesh: &HalfEdgeMesh,
+    ) -> CommandResult<BlendOperation> {
+        let (first, second) = size_pair(context, "distance")?
+            .ok_or_else(|| CommandError::InvalidInput("No chamfer distance specified".to_string()))?;
+        let blend = match context.get_option("angle") {
+            Some(angle) => {
+                let degrees: f64 = angle
+                    .parse()
+                    .map_err(|_| CommandError::InvalidInput(format!("Invalid angle: {}", angle)))?;
+                EdgeBlend::chamfer_angle(first, degrees.to_radians())
+            }
+            None => EdgeBlend::chamfer_distances(first, second),
+        };
+        let edges = edges_from_options(context, mesh)?.into_iter().flatten();
+        Ok(BlendOperation::new().with_edges(edges.map(|edge| (edge, blend))))
+    }
+}
+
+impl Default for ChamferEdgeCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Command for ChamferEdgeCommand {
+    fn name(&self) -> &str {
+        "CHAMFEREDGE"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["CE"]
+    }
+
+    fn description(&self) -> &str {
+        "Bevel edges of a solid by distances or a distance and angle"
+    }
+
+    fn usage(&self) -> &str {
+        "CHAMFEREDGE edges=<id,...> distance=<d|first:second> [angle=<degrees>] [chain=on] [solid=<id>]"
+    }
+
+    fn execute(&mut self, context: &mut CommandContext) -> CommandResult {
+        let result = self.edit.execute(context, self.operation.as_ref(), Self::operation_from_options);
+        self.state = match &result {
+            Ok(()) => CommandState::Completed,
+            Err(error) => CommandState::Failed(error.to_string()),
+        };
+        result
+    }
+
+    fn undo(&mut self, context: &mut CommandContext) -> CommandResult {
+        self.edit.undo(context)?;
+        self.state = CommandState::AwaitingInput;
+        Ok(())
+    }
+
+    fn redo(&mut self, context: &mut CommandContext) -> CommandResult {
+        if !self.edit.redo(context) {
+            return self.execute(context);
+        }
+        self.state = CommandState::Completed;
+        Ok(())
+    }
+
+    fn state(&self) -> CommandState {
+        self.state.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::history::UndoStack;
+    use crate::core::{Point3, Vector3};
+    use crate::engine3d::boolean::MeshValidity;
+    use crate::engine3d::topology::ExtrudeOperation;
+
+    fn volume(context: &CommandContext, solid: EntityId) -> f64 {
+        let mesh = SolidEdit::mesh(context, &solid).unwrap();
+        MeshValidity::check(mesh).unwrap().volume
+    }
+
+    #[test]
+    fn test_blend_commands_undo_and_redo() {
+        let square = [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)].map(|(x, y)| Point3::new(x, y, 0.0));
+        let extrude = ExtrudeOperation {
+            direction: Vector3::new(0.0, 0.0, 2.0),
+            capped: true,
+            ..Default::default()
+        };
+        let cube = extrude.extrude_profile(&square).unwrap();
+        let edge = cube.edge_handles()[0];
+
+        let mut context = CommandContext::new(Document::new());
+        let solid = context.document.add_entity(Box::new(cube));
+        let mut stack = UndoStack::new();
+
+        // Command-line chamfer on the selected solid
+        context = context
+            .with_selection(SelectionSet::from_entities(vec![solid]))
+            .with_option("edges", edge.0.to_string())
+            .with_option("distance", "0.2");
+        let mut chamfer = ChamferEdgeCommand::new();
+        chamfer.execute(&mut context).unwrap();
+        stack.push(chamfer.clone_box(), None, "CHAMFEREDGE".to_string());
+        assert!((volume(&context, solid) - 7.96).abs() < 1e-9);
+
+        stack.undo(&mut context).unwrap();
+        assert!((volume(&context, solid) - 8.0).abs() < 1e-9);
+        stack.redo(&mut context).unwrap();
+        assert!((volume(&context, solid) - 7.96).abs() < 1e-9);
+        stack.undo(&mut context).unwrap();
+
+        // Oversized fillets fail and leave the solid alone
+        let operation = BlendOperation::new().with_edges([(edge, EdgeBlend::fillet(3.0))]);
+        let mut fillet = FilletEdgeCommand::with_operation(solid, operation);
+        assert!(matches!(fillet.execute(&mut context), Err(CommandError::GeometricError(_))));
+        assert!(matches!(fillet.state(), CommandState::Failed(_)));
+        assert!((volume(&context, solid) - 8.0).abs() < 1e-9);
+
+        let mut fillet = FilletEdgeCommand::new();
+        context = context.with_option("radius", "0.5");
+        fillet.execute(&mut context).unwrap();
+        assert!(volume(&context, solid) < 8.0);
+        fillet.undo(&mut context).unwrap();
+        assert!((volume(&context, solid) - 8.0).abs() < 1e-9);
+    }
+}
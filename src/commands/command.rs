@@ -1,8 +1,13 @@
 // Command trait and types for CADDY CAD system
 // Provides the foundation for all commands with undo/redo support
 
+use crate::io::parameters::ParameterValues;
+use crate::io::readout::{Alignment, ReadoutFormat};
+use crate::io::units::Unit;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 
 /// Result type for command operations
@@ -96,7 +101,7 @@ impl Point {
 }
 
 /// Entity ID for identifying geometric entities
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct EntityId(pub u64);
 
 impl EntityId {
@@ -155,13 +160,57 @@ impl Default for SelectionSet {
     }
 }
 
+/// Revision metadata kept for every entity the document has seen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityRevision {
+    /// Per-entity counter, 1 on creation and incremented on every change
+    pub revision: u64,
+    /// Document version at which the entity last changed
+    pub version: u64,
+    /// Time of the last change
+    pub modified_at: DateTime<Utc>,
+    /// Name of the command that made the last change, if any
+    pub modified_by: Option<String>,
+    /// The entity has been removed; kept so removals show up in change queries
+    pub deleted: bool,
+}
+
+/// One entity's latest change, as sent to API and sync clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityChange {
+    pub id: EntityId,
+    pub revision: EntityRevision,
+}
+
+/// Everything that changed after a version a client has already seen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeSet {
+    /// Version the client asked from
+    pub since: u64,
+    /// Document version the changes bring the client up to
+    pub version: u64,
+    /// Changed entities, oldest change first
+    pub changes: Vec<EntityChange>,
+}
+
 /// Document state containing all entities and layers
+///
+/// Every add, remove, restore and mutable access bumps the document version
+/// and the affected entity's revision, so consumers can ask for everything
+/// that changed since a version they have already seen. Writing to
+/// `entities` directly bypasses this tracking.
 #[derive(Debug)]
 pub struct Document {
     pub entities: HashMap<EntityId, Box<dyn Any + Send + Sync>>,
     pub current_layer: String,
     pub layers: Vec<String>,
     next_entity_id: u64,
+    version: u64,
+    revisions: HashMap<EntityId, EntityRevision>,
+    /// Version of each entity's latest change -> entity, so change queries
+    /// only visit the entities that actually changed
+    changes: BTreeMap<u64, EntityId>,
+    change_source: Option<String>,
 }
 
 impl Document {
@@ -171,6 +220,10 @@ impl Document {
             current_layer: "0".to_string(),
             layers: vec!["0".to_string()],
             next_entity_id: 1,
+            version: 0,
+            revisions: HashMap::new(),
+            changes: BTreeMap::new(),
+            change_source: None,
         }
     }
 
@@ -183,24 +236,121 @@ impl Document {
     pub fn add_entity(&mut self, entity: Box<dyn Any + Send + Sync>) -> EntityId {
         let id = self.allocate_entity_id();
         self.entities.insert(id, entity);
+        self.record_change(id, false);
         id
     }
 
+    /// Insert an entity under a known ID (e.g. restoring it on undo),
+    /// returning the entity it replaced
+    pub fn insert_entity(
+        &mut self,
+        id: EntityId,
+        entity: Box<dyn Any + Send + Sync>,
+    ) -> Option<Box<dyn Any + Send + Sync>> {
+        self.next_entity_id = self.next_entity_id.max(id.0 + 1);
+        let previous = self.entities.insert(id, entity);
+        self.record_change(id, false);
+        previous
+    }
+
     pub fn remove_entity(&mut self, id: &EntityId) -> Option<Box<dyn Any + Send + Sync>> {
-        self.entities.remove(id)
+        let removed = self.entities.remove(id);
+        if removed.is_some() {
+            self.record_change(*id, true);
+        }
+        removed
     }
 
     pub fn get_entity(&self, id: &EntityId) -> Option<&Box<dyn Any + Send + Sync>> {
         self.entities.get(id)
     }
 
+    /// Mutable access to an entity; counts as a modification
     pub fn get_entity_mut(&mut self, id: &EntityId) -> Option<&mut Box<dyn Any + Send + Sync>> {
+        if self.entities.contains_key(id) {
+            self.record_change(*id, false);
+        }
         self.entities.get_mut(id)
     }
 
+    /// Mark an entity as modified without touching its data
+    pub fn touch_entity(&mut self, id: &EntityId) -> bool {
+        let exists = self.entities.contains_key(id);
+        if exists {
+            self.record_change(*id, false);
+        }
+        exists
+    }
+
     pub fn entity_count(&self) -> usize {
         self.entities.len()
     }
+
+    /// Current document version; increases monotonically with every change
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Revision metadata for an entity, including removed ones
+    pub fn entity_revision(&self, id: &EntityId) -> Option<&EntityRevision> {
+        self.revisions.get(id)
+    }
+
+    /// Entities changed (added, modified or removed) after `version`, oldest first
+    pub fn changes_since(&self, version: u64) -> Vec<(EntityId, &EntityRevision)> {
+        self.changes
+            .range(version.saturating_add(1)..)
+            .map(|(_, id)| (*id, &self.revisions[id]))
+            .collect()
+    }
+
+    /// Changes after `version` in the form served to API and sync clients
+    pub fn change_set(&self, since: u64) -> ChangeSet {
+        let changes = self
+            .changes_since(since)
+            .into_iter()
+            .map(|(id, rev)| EntityChange { id, revision: rev.clone() })
+            .collect();
+        ChangeSet { since, version: self.version, changes }
+    }
+
+    /// Drop removal records at or before `version` once every consumer has seen them
+    pub fn prune_deleted(&mut self, version: u64) {
+        let pruned: Vec<(u64, EntityId)> = self
+            .changes
+            .range(..=version)
+            .filter(|(_, id)| self.revisions[*id].deleted)
+            .map(|(v, id)| (*v, *id))
+            .collect();
+        for (v, id) in pruned {
+            self.changes.remove(&v);
+            self.revisions.remove(&id);
+        }
+    }
+
+    /// Set the command name recorded as `modified_by` for subsequent changes
+    pub fn set_change_source(&mut self, source: Option<String>) {
+        self.change_source = source;
+    }
+
+    fn record_change(&mut self, id: EntityId, deleted: bool) {
+        self.version += 1;
+        if let Some(rev) = self.revisions.get(&id) {
+            self.changes.remove(&rev.version);
+        }
+        self.changes.insert(self.version, id);
+        let revision = self.revisions.get(&id).map_or(0, |rev| rev.revision) + 1;
+        self.revisions.insert(
+            id,
+            EntityRevision {
+                revision,
+                version: self.version,
+                modified_at: Utc::now(),
+                modified_by: self.change_source.clone(),
+                deleted,
+            },
+        );
+    }
 }
 
 impl Default for Document {
@@ -219,6 +369,12 @@ pub struct CommandContext {
     pub options: HashMap<String, String>,
     /// Whether to prompt for missing inputs
     pub interactive: bool,
+    /// How inquiry commands report coordinates, distances and angles
+    pub readout: ReadoutFormat,
+    /// Alignment for station/offset readouts
+    pub alignment: Option<Alignment>,
+    /// Evaluated document parameters, for counts and values given as expressions
+    pub parameters: ParameterValues,
 }
 
 impl CommandContext {
@@ -228,9 +384,27 @@ impl CommandContext {
             selection: SelectionSet::new(),
             options: HashMap::new(),
             interactive: true,
+            readout: ReadoutFormat::default(),
+            alignment: None,
+            parameters: ParameterValues::empty(Unit::Decimal),
         }
     }
 
+    pub fn with_readout(mut self, readout: ReadoutFormat) -> Self {
+        self.readout = readout;
+        self
+    }
+
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = Some(alignment);
+        self
+    }
+
+    pub fn with_parameters(mut self, parameters: ParameterValues) -> Self {
+        self.parameters = parameters;
+        self
+    }
+
     pub fn with_selection(mut self, selection: SelectionSet) -> Self {
         self.selection = selection;
         self
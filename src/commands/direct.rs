@@ -0,0 +1,241 @@
+// Direct modeling commands for CADDY CAD system
+// Push/pull of solid faces without a feature history, with undo/redo
+
+use super::command::*;
+use crate::engine3d::brep::{Brep, BrepError, FaceId};
+use crate::engine3d::direct::push_pull;
+use crate::engine3d::mesh::HalfEdgeMesh;
+use crate::engine3d::tessellation::TessellationSettings;
+use std::any::Any;
+
+// ==================== PUSH/PULL COMMAND ====================
+
+/// Offset a planar face of a solid along its normal
+///
+/// The solid is a [`HalfEdgeMesh`] entity: the one given, the `solid=<id>`
+/// option or the first selected. The face is `face=<id>`, an index into the
+/// mesh's faces, and `distance=<d>` pulls it out for positive values and
+/// pushes it in for negative ones. Faces coplanar with or tangent to it move
+/// along, so a triangulated import moves as a whole face.
+#[derive(Clone)]
+pub struct PushPullCommand {
+    solid: Option<EntityId>,
+    face: Option<usize>,
+    distance: Option<f64>,
+    before: Option<HalfEdgeMesh>,
+    after: Option<HalfEdgeMesh>,
+    state: CommandState,
+}
+
+impl PushPullCommand {
+    pub fn new() -> Self {
+        Self {
+            solid: None,
+            face: None,
+            distance: None,
+            before: None,
+            after: None,
+            state: CommandState::AwaitingInput,
+        }
+    }
+
+    /// Command moving face `face` of the solid stored as entity `solid`
+    pub fn with_face(solid: EntityId, face: usize, distance: f64) -> Self {
+        Self {
+            solid: Some(solid),
+            face: Some(face),
+            distance: Some(distance),
+            ..Self::new()
+        }
+    }
+
+    fn mesh<'a>(context: &'a CommandContext, id: &EntityId) -> Option<&'a HalfEdgeMesh> {
+        context.document.get_entity(id)?.downcast_ref::<HalfEdgeMesh>()
+    }
+
+    /// The solid given, the `solid=<id>` option, or the first selected solid
+    fn resolve(&self, context: &CommandContext) -> CommandResult<EntityId> {
+        let id = match (self.solid, context.get_option("solid")) {
+            (Some(id), _) => id,
+            (None, Some(value)) => value
+                .parse()
+                .map(EntityId::new)
+                .map_err(|_| CommandError::InvalidInput(format!("Invalid solid: {}", value)))?,
+            (None, None) => {
+                return context
+                    .selection
+                    .entities
+                    .iter()
+                    .copied()
+                    .find(|id| Self::mesh(context, id).is_some())
+                    .ok_or_else(|| CommandError::InvalidSelection("Select a solid to push/pull".to_string()));
+            }
+        };
+        match Self::mesh(context, &id) {
+            Some(_) => Ok(id),
+            None => Err(CommandError::EntityNotFound(format!("No solid {}", id.0))),
+        }
+    }
+
+    fn option<T: std::str::FromStr>(context: &CommandContext, key: &str) -> CommandResult<T> {
+        let value = context
+            .get_option(key)
+            .ok_or_else(|| CommandError::InvalidInput(format!("No {} specified", key)))?;
+        value
+            .trim()
+            .trim_start_matches('#')
+            .parse()
+            .map_err(|_| CommandError::InvalidInput(format!("Invalid {}: {}", key, value)))
+    }
+
+    fn push_pull(&mut self, context: &mut CommandContext) -> CommandResult {
+        let solid = self.resolve(context)?;
+        let face = match self.face {
+            Some(face) => face,
+            None => Self::option(context, "face")?,
+        };
+        let distance = match self.distance {
+            Some(distance) => distance,
+            None => Self::option(context, "distance")?,
+        };
+        let mesh = Self::mesh(context, &solid)
+            .ok_or_else(|| CommandError::EntityNotFound(format!("No solid {}", solid.0)))?;
+        if face >= mesh.face_handles().len() {
+            return Err(CommandError::EntityNotFound(format!("No face {} on solid {}", face, solid.0)));
+        }
+
+        let geometric = |e: BrepError| CommandError::GeometricError(e.to_string());
+        let mut brep = Brep::from_mesh(mesh).map_err(geometric)?;
+        push_pull(&mut brep, FaceId(face), distance).map_err(geometric)?;
+        let moved = brep.to_mesh(&TessellationSettings::default()).map_err(geometric)?;
+
+        self.before = Some(mesh.clone());
+        self.after = None;
+        self.solid = Some(solid);
+        context.document.insert_entity(solid, Box::new(moved));
+        Ok(())
+    }
+}
+
+impl Default for PushPullCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Command for PushPullCommand {
+    fn name(&self) -> &str {
+        "PUSHPULL"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["PP"]
+    }
+
+    fn description(&self) -> &str {
+        "Offset a planar face of a solid, moving the faces around it"
+    }
+
+    fn usage(&self) -> &str {
+        "PUSHPULL face=<id> distance=<d> [solid=<id>]"
+    }
+
+    fn execute(&mut self, context: &mut CommandContext) -> CommandResult {
+        let result = self.push_pull(context);
+        self.state = match &result {
+            Ok(()) => CommandState::Completed,
+            Err(error) => CommandState::Failed(error.to_string()),
+        };
+        result
+    }
+
+    fn undo(&mut self, context: &mut CommandContext) -> CommandResult {
+        let (Some(solid), Some(before)) = (self.solid, self.before.take()) else {
+            return Err(CommandError::InvalidState("Push/pull has not been applied".to_string()));
+        };
+        self.after = Self::mesh(context, &solid).cloned();
+        context.document.insert_entity(solid, Box::new(before));
+        self.state = CommandState::AwaitingInput;
+        Ok(())
+    }
+
+    fn redo(&mut self, context: &mut CommandContext) -> CommandResult {
+        let (Some(solid), Some(after)) = (self.solid, self.after.take()) else {
+            return self.execute(context);
+        };
+        self.before = Self::mesh(context, &solid).cloned();
+        context.document.insert_entity(solid, Box::new(after));
+        self.state = CommandState::Completed;
+        Ok(())
+    }
+
+    fn state(&self) -> CommandState {
+        self.state.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::history::UndoStack;
+    use crate::core::{Point3, Vector3};
+    use crate::engine3d::boolean::MeshValidity;
+    use crate::engine3d::topology::ExtrudeOperation;
+
+    fn volume(context: &CommandContext, solid: EntityId) -> f64 {
+        let mesh = PushPullCommand::mesh(context, &solid).unwrap();
+        MeshValidity::check(mesh).unwrap().volume
+    }
+
+    #[test]
+    fn test_push_pull_command_undo_and_redo() {
+        let square = [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)].map(|(x, y)| Point3::new(x, y, 0.0));
+        let extrude = ExtrudeOperation {
+            direction: Vector3::new(0.0, 0.0, 2.0),
+            capped: true,
+            ..Default::default()
+        };
+        let cube = extrude.extrude_profile(&square).unwrap();
+        let top = cube
+            .face_handles()
+            .iter()
+            .position(|&f| {
+                let corners = cube.face_vertices(f).unwrap();
+                corners.iter().all(|&v| cube.get_vertex(v).unwrap().position.z > 1.5)
+            })
+            .unwrap();
+
+        let mut context = CommandContext::new(Document::new());
+        let solid = context.document.add_entity(Box::new(cube));
+        let mut stack = UndoStack::new();
+
+        context = context
+            .with_selection(SelectionSet::from_entities(vec![solid]))
+            .with_option("face", top.to_string())
+            .with_option("distance", "1");
+        let mut pull = PushPullCommand::new();
+        pull.execute(&mut context).unwrap();
+        stack.push(pull.clone_box(), None, "PUSHPULL".to_string());
+        assert!((volume(&context, solid) - 12.0).abs() < 1e-9);
+
+        stack.undo(&mut context).unwrap();
+        assert!((volume(&context, solid) - 8.0).abs() < 1e-9);
+        stack.redo(&mut context).unwrap();
+        assert!((volume(&context, solid) - 12.0).abs() < 1e-9);
+        stack.undo(&mut context).unwrap();
+
+        // Pushing through the opposite face fails and leaves the solid alone
+        let mut push = PushPullCommand::with_face(solid, top, -3.0);
+        assert!(matches!(push.execute(&mut context), Err(CommandError::GeometricError(_))));
+        assert!(matches!(push.state(), CommandState::Failed(_)));
+        assert!((volume(&context, solid) - 8.0).abs() < 1e-9);
+    }
+}
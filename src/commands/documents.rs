@@ -0,0 +1,389 @@
+// Open document set for CADDY CAD system
+// Tracks every open drawing as a tab, gives each its own undo history and
+// command context, and moves entities between drawings through a shared
+// clipboard
+
+use super::command::{CommandContext, Document as CommandDocument};
+use super::history::UndoStack;
+use super::processor::CommandProcessor;
+use crate::io::clipboard::{self, ClipboardContent, ClipboardError, PasteOptions, PasteReport};
+use crate::io::document::Document;
+use std::path::PathBuf;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Document set errors
+#[derive(Debug, Error)]
+pub enum DocumentSetError {
+    /// No open document has this ID
+    #[error("Document not open: {0}")]
+    NotFound(Uuid),
+
+    /// The operation needs an active document and there is none
+    #[error("No active document")]
+    NoActiveDocument,
+
+    /// Paste with nothing copied
+    #[error("Clipboard is empty")]
+    EmptyClipboard,
+
+    /// Closing would lose unsaved changes
+    #[error("Document '{0}' has unsaved changes")]
+    Unsaved(String),
+
+    /// Copy or paste failed
+    #[error(transparent)]
+    Clipboard(#[from] ClipboardError),
+}
+
+/// Result type for document set operations
+pub type DocumentSetResult<T> = Result<T, DocumentSetError>;
+
+/// One open drawing
+pub struct OpenDocument {
+    /// Tab title
+    pub title: String,
+    /// File the drawing was loaded from or last saved to
+    pub path: Option<PathBuf>,
+    /// The drawing
+    pub model: Document,
+    /// Context commands run against while this document is active
+    pub context: CommandContext,
+    /// Unsaved changes
+    pub modified: bool,
+    /// Undo history; parked here while another document is active, and
+    /// lent to the command processor while this one is
+    history: UndoStack,
+}
+
+impl OpenDocument {
+    /// Document ID
+    pub fn id(&self) -> Uuid {
+        self.model.id
+    }
+
+    /// Undo history, if this document is not the active one
+    pub fn parked_history(&self) -> &UndoStack {
+        &self.history
+    }
+}
+
+/// The set of open documents, one of which is active
+///
+/// The command processor only ever holds the active document's undo
+/// history. Switching documents parks that history back in its tab and lends
+/// the new document's history to the processor, so undo never reaches into
+/// a drawing the user is not looking at.
+#[derive(Default)]
+pub struct DocumentSet {
+    documents: Vec<OpenDocument>,
+    active: Option<usize>,
+    clipboard: Option<ClipboardContent>,
+}
+
+impl DocumentSet {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a document as a new tab; it becomes active on [`Self::activate`]
+    pub fn open(&mut self, model: Document, title: impl Into<String>, path: Option<PathBuf>) -> Uuid {
+        let id = model.id;
+        self.documents.push(OpenDocument {
+            title: title.into(),
+            path,
+            model,
+            context: CommandContext::new(CommandDocument::new()),
+            modified: false,
+            history: UndoStack::new(),
+        });
+        id
+    }
+
+    /// Make a document active, swapping undo histories in the processor
+    ///
+    /// A command still waiting for input is cancelled first; its remaining
+    /// prompts belong to the document it was started in.
+    pub fn activate(&mut self, id: Uuid, processor: &mut CommandProcessor) -> DocumentSetResult<()> {
+        let index = self.index_of(id)?;
+        if self.active == Some(index) {
+            return Ok(());
+        }
+        if processor.current_state().is_some() {
+            let _ = processor.cancel_current();
+        }
+        match self.active {
+            Some(current) => self.park(current, processor),
+            // Whatever the processor held belonged to no open document
+            None => processor.history_mut().clear(),
+        }
+        std::mem::swap(processor.history_mut(), &mut self.documents[index].history);
+        self.active = Some(index);
+        Ok(())
+    }
+
+    /// Switch to the next tab, wrapping around
+    pub fn next(&mut self, processor: &mut CommandProcessor) -> DocumentSetResult<()> {
+        self.step(1, processor)
+    }
+
+    /// Switch to the previous tab, wrapping around
+    pub fn previous(&mut self, processor: &mut CommandProcessor) -> DocumentSetResult<()> {
+        self.step(self.documents.len().saturating_sub(1), processor)
+    }
+
+    /// Close a document, refusing if it has unsaved changes unless
+    /// `discard` is set. Closing the active tab activates its neighbour.
+    pub fn close(
+        &mut self,
+        id: Uuid,
+        processor: &mut CommandProcessor,
+        discard: bool,
+    ) -> DocumentSetResult<OpenDocument> {
+        let index = self.index_of(id)?;
+        if self.documents[index].modified && !discard {
+            return Err(DocumentSetError::Unsaved(self.documents[index].title.clone()));
+        }
+
+        let was_active = self.active == Some(index);
+        if was_active {
+            if processor.current_state().is_some() {
+                let _ = processor.cancel_current();
+            }
+            self.park(index, processor);
+            self.active = None;
+        } else if let Some(active) = self.active.filter(|&active| active > index) {
+            self.active = Some(active - 1);
+        }
+
+        let closed = self.documents.remove(index);
+        if was_active && !self.documents.is_empty() {
+            let neighbour = self.documents[index.min(self.documents.len() - 1)].id();
+            self.activate(neighbour, processor)?;
+        }
+        Ok(closed)
+    }
+
+    /// The active document
+    pub fn active(&self) -> Option<&OpenDocument> {
+        self.active.map(|index| &self.documents[index])
+    }
+
+    /// The active document, mutably
+    pub fn active_mut(&mut self) -> Option<&mut OpenDocument> {
+        self.active.map(|index| &mut self.documents[index])
+    }
+
+    /// Look up an open document
+    pub fn get(&self, id: Uuid) -> Option<&OpenDocument> {
+        self.documents.iter().find(|doc| doc.id() == id)
+    }
+
+    /// Look up an open document mutably
+    pub fn get_mut(&mut self, id: Uuid) -> Option<&mut OpenDocument> {
+        self.documents.iter_mut().find(|doc| doc.id() == id)
+    }
+
+    /// Open documents in tab order
+    pub fn documents(&self) -> &[OpenDocument] {
+        &self.documents
+    }
+
+    /// Number of open documents
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Whether no document is open
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Copy entities of a document to the shared clipboard, returning how
+    /// many were copied
+    pub fn copy(&mut self, id: Uuid, entities: &[Uuid]) -> DocumentSetResult<usize> {
+        let doc = self.get(id).ok_or(DocumentSetError::NotFound(id))?;
+        let content = ClipboardContent::copy(&doc.model, entities)?;
+        let count = content.entities.len();
+        self.clipboard = Some(content);
+        Ok(count)
+    }
+
+    /// Copy entities to the clipboard and delete them from their document
+    pub fn cut(&mut self, id: Uuid, entities: &[Uuid]) -> DocumentSetResult<usize> {
+        let count = self.copy(id, entities)?;
+        let doc = self.get_mut(id).ok_or(DocumentSetError::NotFound(id))?;
+        for entity in entities {
+            doc.model.remove_entity(*entity);
+        }
+        doc.modified = true;
+        Ok(count)
+    }
+
+    /// Paste the clipboard into a document
+    pub fn paste(&mut self, id: Uuid, options: &PasteOptions) -> DocumentSetResult<PasteReport> {
+        let content = self.clipboard.as_ref().ok_or(DocumentSetError::EmptyClipboard)?;
+        let doc = self
+            .documents
+            .iter_mut()
+            .find(|doc| doc.model.id == id)
+            .ok_or(DocumentSetError::NotFound(id))?;
+        let report = content.paste_into(&mut doc.model, options)?;
+        doc.modified = true;
+        Ok(report)
+    }
+
+    /// Drag entities from one document into another, leaving the clipboard
+    /// untouched
+    pub fn drag(
+        &mut self,
+        source: Uuid,
+        target: Uuid,
+        entities: &[Uuid],
+        options: &PasteOptions,
+    ) -> DocumentSetResult<PasteReport> {
+        let from = self.index_of(source)?;
+        let to = self.index_of(target)?;
+        if from == to {
+            return Err(ClipboardError::SameDocument.into());
+        }
+
+        let (low, high) = self.documents.split_at_mut(from.max(to));
+        let (source, target) = if from < to {
+            (&mut low[from], &mut high[0])
+        } else {
+            (&mut high[0], &mut low[to])
+        };
+        let report = clipboard::transfer(&mut source.model, &mut target.model, entities, options)?;
+        source.modified = true;
+        target.modified = true;
+        Ok(report)
+    }
+
+    /// What was last copied, if anything
+    pub fn clipboard(&self) -> Option<&ClipboardContent> {
+        self.clipboard.as_ref()
+    }
+
+    fn index_of(&self, id: Uuid) -> DocumentSetResult<usize> {
+        self.documents
+            .iter()
+            .position(|doc| doc.id() == id)
+            .ok_or(DocumentSetError::NotFound(id))
+    }
+
+    /// Take the processor's history back into the tab at `index`
+    fn park(&mut self, index: usize, processor: &mut CommandProcessor) {
+        std::mem::swap(processor.history_mut(), &mut self.documents[index].history);
+        processor.history_mut().clear();
+    }
+
+    fn step(&mut self, by: usize, processor: &mut CommandProcessor) -> DocumentSetResult<()> {
+        let current = self.active.ok_or(DocumentSetError::NoActiveDocument)?;
+        let id = self.documents[(current + by) % self.documents.len()].id();
+        self.activate(id, processor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::command::{Command, CommandResult};
+    use crate::commands::registry::CommandRegistry;
+    use crate::io::document::{Entity, GeometryType, Line, Vec3};
+
+    #[derive(Clone)]
+    struct Noop;
+
+    impl Command for Noop {
+        fn name(&self) -> &str {
+            "NOOP"
+        }
+
+        fn execute(&mut self, _context: &mut CommandContext) -> CommandResult {
+            Ok(())
+        }
+
+        fn undo(&mut self, _context: &mut CommandContext) -> CommandResult {
+            Ok(())
+        }
+
+        fn clone_box(&self) -> Box<dyn Command> {
+            Box::new(self.clone())
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    fn drawing(lines: usize) -> (Document, Vec<Uuid>) {
+        let mut doc = Document::new();
+        let ids = (0..lines)
+            .map(|i| {
+                doc.add_entity(Entity::new(
+                    GeometryType::Line(Line {
+                        start: Vec3::new(i as f64, 0.0, 0.0),
+                        end: Vec3::new(i as f64, 1.0, 0.0),
+                    }),
+                    "0".to_string(),
+                ))
+            })
+            .collect();
+        (doc, ids)
+    }
+
+    #[test]
+    fn test_histories_follow_active_document() {
+        let mut processor = CommandProcessor::new(CommandRegistry::new());
+        let mut set = DocumentSet::new();
+        let a = set.open(drawing(0).0, "A", None);
+        let b = set.open(drawing(0).0, "B", None);
+
+        set.activate(a, &mut processor).unwrap();
+        processor.history_mut().push(Box::new(Noop), None, "first".to_string());
+        processor.history_mut().push(Box::new(Noop), None, "second".to_string());
+
+        set.next(&mut processor).unwrap();
+        assert_eq!(set.active().unwrap().id(), b);
+        assert!(!processor.history().can_undo());
+        assert_eq!(set.get(a).unwrap().parked_history().undo_count(), 2);
+
+        processor.history_mut().push(Box::new(Noop), None, "third".to_string());
+        set.previous(&mut processor).unwrap();
+        assert_eq!(processor.history().undo_count(), 2);
+        assert_eq!(set.get(b).unwrap().parked_history().undo_count(), 1);
+
+        set.get_mut(a).unwrap().modified = true;
+        assert!(matches!(set.close(a, &mut processor, false), Err(DocumentSetError::Unsaved(_))));
+        let closed = set.close(a, &mut processor, true).unwrap();
+        assert_eq!(closed.parked_history().undo_count(), 2);
+        assert_eq!(set.active().unwrap().id(), b);
+        assert_eq!(processor.history().undo_count(), 1);
+    }
+
+    #[test]
+    fn test_copy_paste_and_drag_between_documents() {
+        let mut processor = CommandProcessor::new(CommandRegistry::new());
+        let mut set = DocumentSet::new();
+        let (doc, ids) = drawing(3);
+        let a = set.open(doc, "A", None);
+        let b = set.open(drawing(0).0, "B", None);
+        set.activate(a, &mut processor).unwrap();
+
+        assert!(matches!(set.paste(b, &PasteOptions::default()), Err(DocumentSetError::EmptyClipboard)));
+        assert_eq!(set.copy(a, &ids[..2]).unwrap(), 2);
+        set.paste(b, &PasteOptions::default()).unwrap();
+        set.paste(b, &PasteOptions::default()).unwrap();
+        assert_eq!(set.get(b).unwrap().model.entities.len(), 4);
+        assert!(set.get(b).unwrap().modified);
+        assert!(!set.get(a).unwrap().modified);
+
+        let report = set.drag(a, b, &ids[2..], &PasteOptions::default()).unwrap();
+        assert_eq!(report.entities.len(), 1);
+        assert_eq!(set.get(a).unwrap().model.entities.len(), 2);
+        assert_eq!(set.get(b).unwrap().model.entities.len(), 5);
+        assert!(set.drag(b, b, &report.entities, &PasteOptions::default()).is_err());
+    }
+}
@@ -74,7 +74,7 @@ impl Command for EraseCommand {
     fn undo(&mut self, context: &mut CommandContext) -> CommandResult {
         // Restore deleted entities
         for (entity_id, entity) in self.deleted_entities.drain() {
-            context.document.entities.insert(entity_id, entity);
+            context.document.insert_entity(entity_id, entity);
         }
 
         self.state = CommandState::AwaitingInput;
@@ -286,7 +286,7 @@ impl Command for CutCommand {
     fn undo(&mut self, context: &mut CommandContext) -> CommandResult {
         // Restore deleted entities
         for (entity_id, entity) in self.deleted_entities.drain() {
-            context.document.entities.insert(entity_id, entity);
+            context.document.insert_entity(entity_id, entity);
         }
 
         self.state = CommandState::AwaitingInput;
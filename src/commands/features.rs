@@ -0,0 +1,339 @@
+// Feature history commands for CADDY CAD system
+// Edits the feature tree of a part stored in the document, with undo/redo
+
+use super::command::*;
+use crate::engine3d::features::{FeatureError, FeatureId, FeatureKind, FeatureTree};
+use std::any::Any;
+
+// ==================== FEATURE COMMAND ====================
+
+/// Change to a part's feature history
+#[derive(Debug, Clone)]
+pub enum FeatureEdit {
+    /// Add a feature at the rollback marker
+    Add { name: String, kind: FeatureKind },
+    /// Replace a feature's definition
+    Edit { feature: FeatureId, kind: FeatureKind },
+    /// Rename a feature
+    Rename { feature: FeatureId, name: String },
+    /// Move a feature to a new position
+    Reorder { feature: FeatureId, index: usize },
+    /// Suppress or unsuppress a feature
+    Suppress { feature: FeatureId, suppressed: bool },
+    /// Move the rollback marker; `None` rolls forward to the end
+    Rollback { position: Option<usize> },
+    /// Remove a feature nothing depends on
+    Remove { feature: FeatureId },
+}
+
+/// Apply one edit to a part's feature tree
+///
+/// The part is a [`FeatureTree`] entity: the one given, or the first
+/// selected. Undo and redo swap whole trees, so dependent features come back
+/// exactly as they were without regenerating. From the command line the
+/// structural edits are available as options: `suppress=<id>`,
+/// `unsuppress=<id>`, `rollback=<position|end>`, `reorder=<id>:<position>`
+/// and `remove=<id>`.
+#[derive(Clone)]
+pub struct FeatureCommand {
+    part: Option<EntityId>,
+    edit: Option<FeatureEdit>,
+    before: Option<FeatureTree>,
+    after: Option<FeatureTree>,
+    added: Option<FeatureId>,
+    state: CommandState,
+}
+
+impl FeatureCommand {
+    pub fn new() -> Self {
+        Self {
+            part: None,
+            edit: None,
+            before: None,
+            after: None,
+            added: None,
+            state: CommandState::AwaitingInput,
+        }
+    }
+
+    /// Command applying `edit` to the tree stored as entity `part`
+    pub fn with_edit(part: EntityId, edit: FeatureEdit) -> Self {
+        Self {
+            part: Some(part),
+            edit: Some(edit),
+            ..Self::new()
+        }
+    }
+
+    /// Feature created by an `Add` edit
+    pub fn added(&self) -> Option<FeatureId> {
+        self.added
+    }
+
+    fn resolve_part(&self, context: &CommandContext) -> CommandResult<EntityId> {
+        let is_tree = |id: &EntityId| {
+            context
+                .document
+                .get_entity(id)
+                .is_some_and(|e| e.downcast_ref::<FeatureTree>().is_some())
+        };
+        if let Some(part) = self.part {
+            return if is_tree(&part) {
+                Ok(part)
+            } else {
+                Err(CommandError::EntityNotFound(format!("No feature tree {}", part.0)))
+            };
+        }
+        if let Some(part) = context.get_option("part") {
+            let id = part
+                .parse()
+                .map(EntityId::new)
+                .map_err(|_| CommandError::InvalidInput(format!("Invalid part: {}", part)))?;
+            return if is_tree(&id) {
+                Ok(id)
+            } else {
+                Err(CommandError::EntityNotFound(format!("No feature tree {}", id.0)))
+            };
+        }
+        context
+            .selection
+            .entities
+            .iter()
+            .copied()
+            .find(|id| is_tree(id))
+            .ok_or_else(|| CommandError::InvalidSelection("Select a part with a feature history".to_string()))
+    }
+
+    /// Edit given as command-line options
+    fn edit_from_options(context: &CommandContext) -> CommandResult<FeatureEdit> {
+        let feature = |value: &String| {
+            value
+                .trim_start_matches('#')
+                .parse()
+                .map(FeatureId)
+                .map_err(|_| CommandError::InvalidInput(format!("Invalid feature: {}", value)))
+        };
+        let position = |value: &String| {
+            value
+                .parse::<usize>()
+                .map_err(|_| CommandError::InvalidInput(format!("Invalid position: {}", value)))
+        };
+
+        if let Some(value) = context.get_option("suppress") {
+            Ok(FeatureEdit::Suppress { feature: feature(value)?, suppressed: true })
+        } else if let Some(value) = context.get_option("unsuppress") {
+            Ok(FeatureEdit::Suppress { feature: feature(value)?, suppressed: false })
+        } else if let Some(value) = context.get_option("rollback") {
+            let position = if value.eq_ignore_ascii_case("end") { None } else { Some(position(value)?) };
+            Ok(FeatureEdit::Rollback { position })
+        } else if let Some(value) = context.get_option("reorder") {
+            let (id, index) = value
+                .split_once(':')
+                .ok_or_else(|| CommandError::InvalidInput("Expected reorder=<feature>:<position>".to_string()))?;
+            Ok(FeatureEdit::Reorder {
+                feature: feature(&id.to_string())?,
+                index: position(&index.to_string())?,
+            })
+        } else if let Some(value) = context.get_option("remove") {
+            Ok(FeatureEdit::Remove { feature: feature(value)? })
+        } else {
+            Err(CommandError::InvalidInput("No feature edit specified".to_string()))
+        }
+    }
+
+    fn apply(&mut self, tree: &mut FeatureTree, edit: FeatureEdit) -> Result<(), FeatureError> {
+        match edit {
+            FeatureEdit::Add { name, kind } => {
+                self.added = Some(tree.add(name, kind)?);
+            }
+            FeatureEdit::Edit { feature, kind } => {
+                tree.edit(feature, kind)?;
+            }
+            FeatureEdit::Rename { feature, name } => tree.rename(feature, name)?,
+            FeatureEdit::Reorder { feature, index } => {
+                tree.reorder(feature, index)?;
+            }
+            FeatureEdit::Suppress { feature, suppressed } => {
+                tree.suppress(feature, suppressed)?;
+            }
+            FeatureEdit::Rollback { position } => {
+                tree.rollback_to(position);
+            }
+            FeatureEdit::Remove { feature } => {
+                tree.remove(feature)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn replace_tree(context: &mut CommandContext, part: EntityId, tree: FeatureTree) {
+        context.document.insert_entity(part, Box::new(tree));
+    }
+}
+
+impl Default for FeatureCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Command for FeatureCommand {
+    fn name(&self) -> &str {
+        "FEATURE"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["FEAT"]
+    }
+
+    fn description(&self) -> &str {
+        "Edit, reorder, suppress or roll back features in a part's history"
+    }
+
+    fn usage(&self) -> &str {
+        "FEATURE suppress=<id> | unsuppress=<id> | rollback=<position|end> | reorder=<id>:<position> | remove=<id>"
+    }
+
+    fn execute(&mut self, context: &mut CommandContext) -> CommandResult {
+        let part = self.resolve_part(context)?;
+        let edit = match self.edit.clone() {
+            Some(edit) => edit,
+            None => Self::edit_from_options(context)?,
+        };
+
+        let tree = context
+            .document
+            .get_entity(&part)
+            .and_then(|e| e.downcast_ref::<FeatureTree>())
+            .ok_or_else(|| CommandError::EntityNotFound(format!("No feature tree {}", part.0)))?;
+        let before = tree.clone();
+        let mut after = tree.clone();
+        if let Err(error) = self.apply(&mut after, edit.clone()) {
+            self.state = CommandState::Failed(error.to_string());
+            return Err(CommandError::InvalidInput(error.to_string()));
+        }
+
+        Self::replace_tree(context, part, after);
+        self.part = Some(part);
+        self.edit = Some(edit);
+        self.before = Some(before);
+        self.after = None;
+        self.state = CommandState::Completed;
+        Ok(())
+    }
+
+    fn undo(&mut self, context: &mut CommandContext) -> CommandResult {
+        let (Some(part), Some(before)) = (self.part, self.before.take()) else {
+            return Err(CommandError::InvalidState("Feature edit has not been applied".to_string()));
+        };
+        let current = context
+            .document
+            .get_entity(&part)
+            .and_then(|e| e.downcast_ref::<FeatureTree>())
+            .cloned();
+        self.after = current;
+        Self::replace_tree(context, part, before);
+        self.state = CommandState::AwaitingInput;
+        Ok(())
+    }
+
+    fn redo(&mut self, context: &mut CommandContext) -> CommandResult {
+        let (Some(part), Some(after)) = (self.part, self.after.take()) else {
+            return self.execute(context);
+        };
+        self.before = context
+            .document
+            .get_entity(&part)
+            .and_then(|e| e.downcast_ref::<FeatureTree>())
+            .cloned();
+        Self::replace_tree(context, part, after);
+        self.state = CommandState::Completed;
+        Ok(())
+    }
+
+    fn state(&self) -> CommandState {
+        self.state.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::history::UndoStack;
+    use crate::core::{Point3, Vector3};
+    use crate::engine3d::boolean::{BooleanOp, MeshValidity};
+    use crate::engine3d::features::FeatureStatus;
+    use crate::engine3d::topology::ProfileRegion;
+
+    fn tree(context: &CommandContext, part: EntityId) -> &FeatureTree {
+        context.document.get_entity(&part).unwrap().downcast_ref::<FeatureTree>().unwrap()
+    }
+
+    fn run(stack: &mut UndoStack, context: &mut CommandContext, mut command: FeatureCommand) -> Option<FeatureId> {
+        command.execute(context).unwrap();
+        let added = command.added();
+        stack.push(command.clone_box(), None, "FEATURE".to_string());
+        added
+    }
+
+    #[test]
+    fn test_feature_edits_undo_and_redo() {
+        let mut context = CommandContext::new(Document::new());
+        let part = context.document.add_entity(Box::new(FeatureTree::new()));
+        let mut stack = UndoStack::new();
+
+        let square = ProfileRegion::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 2.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+        ]);
+        let add = |name: &str, kind: FeatureKind| FeatureCommand::with_edit(part, FeatureEdit::Add { name: name.to_string(), kind });
+        let sketch = run(&mut stack, &mut context, add("Sketch", FeatureKind::Sketch { region: square })).unwrap();
+        let pad = run(
+            &mut stack,
+            &mut context,
+            add(
+                "Pad",
+                FeatureKind::Extrude {
+                    sketch,
+                    direction: Vector3::new(0.0, 0.0, 1.0),
+                    draft: 0.0,
+                    combine: BooleanOp::Union,
+                },
+            ),
+        )
+        .unwrap();
+        let volume = |context: &CommandContext| MeshValidity::check(tree(context, part).body().unwrap()).unwrap().volume;
+        assert!((volume(&context) - 4.0).abs() < 1e-9);
+
+        // Command-line suppression, undone and redone through the history
+        context = context.with_selection(SelectionSet::from_entities(vec![part])).with_option("suppress", "#1");
+        run(&mut stack, &mut context, FeatureCommand::new());
+        assert_eq!(tree(&context, part).status(pad), Some(&FeatureStatus::ParentUnavailable(sketch)));
+        assert!(tree(&context, part).body().is_none());
+
+        stack.undo(&mut context).unwrap();
+        assert_eq!(tree(&context, part).status(pad), Some(&FeatureStatus::Ok));
+        assert!((volume(&context) - 4.0).abs() < 1e-9);
+        stack.redo(&mut context).unwrap();
+        assert!(tree(&context, part).body().is_none());
+
+        stack.undo(&mut context).unwrap();
+        stack.undo(&mut context).unwrap();
+        assert_eq!(tree(&context, part).features().len(), 1);
+
+        // Invalid edits leave the tree alone
+        let mut bad = FeatureCommand::with_edit(part, FeatureEdit::Remove { feature: FeatureId(99) });
+        assert!(bad.execute(&mut context).is_err());
+        assert_eq!(tree(&context, part).features().len(), 1);
+    }
+}
@@ -0,0 +1,809 @@
+// Inquiry commands for CADDY CAD system
+// Implements measurement commands (DIST, ID, MEASURE, MEASUREONSURFACE, MASSPROP) that report
+// without modifying
+
+use super::command::*;
+use crate::core::precision::EPSILON;
+use crate::engine3d::geodesic::{GeodesicError, GeodesicPath, GeodesicSettings, GeodesicSolver};
+use crate::geometry::convert::flatten;
+use crate::geometry::enclose::{enclosing_circle, enclosing_sphere, min_area_rect, min_volume_box};
+use crate::geometry::{
+    Arc2D, ArcPolyline, AreaProperties, BSpline, Circle2D, Ellipse2D, EllipticalArc2D, FitSegment,
+    LineSegment2D, NurbsCurve, Point2D, PointCloud, Polygon2D, Polyline2D, Region, TriangleMesh,
+};
+use nalgebra::Point3;
+use std::any::Any;
+
+/// Parse "x,y[,z]" or "x y [z]"
+fn parse_point(input: &str) -> CommandResult<Point> {
+    let values = input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| CommandError::InvalidInput(format!("Invalid point: {}", input)))?;
+
+    match values.as_slice() {
+        [x, y] => Ok(Point::new_2d(*x, *y)),
+        [x, y, z] => Ok(Point::new(*x, *y, *z)),
+        _ => Err(CommandError::InvalidInput(format!("Invalid point: {}", input))),
+    }
+}
+
+// ==================== DIST COMMAND ====================
+
+#[derive(Clone)]
+pub struct DistCommand {
+    first: Option<Point>,
+    second: Option<Point>,
+    report: Option<String>,
+    state: CommandState,
+}
+
+impl DistCommand {
+    pub fn new() -> Self {
+        Self {
+            first: None,
+            second: None,
+            report: None,
+            state: CommandState::AwaitingParameter("first point".to_string()),
+        }
+    }
+
+    /// Measurement text from the last run
+    pub fn report(&self) -> Option<&str> {
+        self.report.as_deref()
+    }
+}
+
+impl Default for DistCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Command for DistCommand {
+    fn name(&self) -> &str {
+        "DIST"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["DI"]
+    }
+
+    fn description(&self) -> &str {
+        "Measure distance and angle between two points"
+    }
+
+    fn usage(&self) -> &str {
+        "DIST <x1,y1> <x2,y2>"
+    }
+
+    fn execute(&mut self, context: &mut CommandContext) -> CommandResult {
+        let first = self.first.ok_or_else(||
+            CommandError::InvalidInput("First point not specified".to_string()))?;
+        let second = self.second.ok_or_else(||
+            CommandError::InvalidInput("Second point not specified".to_string()))?;
+
+        let readout = &context.readout;
+        let (dx, dy, dz) = (second.x - first.x, second.y - first.y, second.z - first.z);
+        let distance = (dx * dx + dy * dy + dz * dz).sqrt();
+
+        self.report = Some(format!(
+            "Distance = {}, Angle in XY Plane = {}, Delta X = {}, Delta Y = {}, Delta Z = {}",
+            readout.format_distance(distance),
+            readout.format_direction(dy.atan2(dx)),
+            readout.format_distance(dx),
+            readout.format_distance(dy),
+            readout.format_distance(dz),
+        ));
+
+        self.state = CommandState::Completed;
+        Ok(())
+    }
+
+    fn undo(&mut self, _context: &mut CommandContext) -> CommandResult {
+        Ok(())
+    }
+
+    fn can_undo(&self) -> bool {
+        false
+    }
+
+    fn state(&self) -> CommandState {
+        self.state.clone()
+    }
+
+    fn process_input(&mut self, input: &str, _context: &mut CommandContext) -> CommandResult {
+        let point = parse_point(input)?;
+        if self.first.is_none() {
+            self.first = Some(point);
+            self.state = CommandState::AwaitingParameter("second point".to_string());
+        } else {
+            self.second = Some(point);
+            self.state = CommandState::Executing;
+        }
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// ==================== ID COMMAND ====================
+
+#[derive(Clone)]
+pub struct IdCommand {
+    point: Option<Point>,
+    report: Option<String>,
+    state: CommandState,
+}
+
+impl IdCommand {
+    pub fn new() -> Self {
+        Self {
+            point: None,
+            report: None,
+            state: CommandState::AwaitingParameter("point".to_string()),
+        }
+    }
+
+    /// Coordinate text from the last run
+    pub fn report(&self) -> Option<&str> {
+        self.report.as_deref()
+    }
+}
+
+impl Default for IdCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Command for IdCommand {
+    fn name(&self) -> &str {
+        "ID"
+    }
+
+    fn description(&self) -> &str {
+        "Report the coordinates, or station and offset, of a point"
+    }
+
+    fn usage(&self) -> &str {
+        "ID <x,y>"
+    }
+
+    fn execute(&mut self, context: &mut CommandContext) -> CommandResult {
+        let point = self.point.ok_or_else(||
+            CommandError::InvalidInput("Point not specified".to_string()))?;
+
+        // Polar has no base for a single point, so it reads as cartesian
+        let mut readout = context.readout.clone();
+        if readout.coordinates == crate::io::readout::CoordinateMode::Polar {
+            readout.coordinates = crate::io::readout::CoordinateMode::Cartesian;
+        }
+        let location = readout.format_point(
+            Point2D::new(point.x, point.y),
+            None,
+            context.alignment.as_ref(),
+        );
+        self.report = Some(format!("{}, Z = {}", location, readout.format_distance(point.z)));
+
+        self.state = CommandState::Completed;
+        Ok(())
+    }
+
+    fn undo(&mut self, _context: &mut CommandContext) -> CommandResult {
+        Ok(())
+    }
+
+    fn can_undo(&self) -> bool {
+        false
+    }
+
+    fn state(&self) -> CommandState {
+        self.state.clone()
+    }
+
+    fn process_input(&mut self, input: &str, _context: &mut CommandContext) -> CommandResult {
+        self.point = Some(parse_point(input)?);
+        self.state = CommandState::Executing;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// ==================== MEASURE COMMAND ====================
+
+/// Chord tolerance for reading curved entities as points
+const MEASURE_FLATTEN_TOLERANCE: f64 = 1e-4;
+
+/// Enclosing shape a MEASURE run reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeasureShape {
+    /// Minimum oriented rectangle, or box once any selected entity is 3D
+    Box,
+    /// Smallest enclosing circle of the XY footprint
+    Circle,
+    /// Smallest enclosing sphere
+    Sphere,
+}
+
+#[derive(Clone)]
+pub struct MeasureCommand {
+    shape: MeasureShape,
+    stock: Option<Vec<f64>>,
+    report: Option<String>,
+    state: CommandState,
+}
+
+impl MeasureCommand {
+    pub fn new() -> Self {
+        Self {
+            shape: MeasureShape::Box,
+            stock: None,
+            report: None,
+            state: CommandState::AwaitingParameter("BOX, CIRCLE, SPHERE or stock size".to_string()),
+        }
+    }
+
+    /// Measurement text from the last run
+    pub fn report(&self) -> Option<&str> {
+        self.report.as_deref()
+    }
+
+    /// Fit check suffix for the report
+    fn fit_text(&self, fits: impl FnOnce(&[f64]) -> CommandResult<bool>) -> CommandResult<String> {
+        let Some(stock) = &self.stock else {
+            return Ok(String::new());
+        };
+        let size = stock.iter().map(|v| v.to_string()).collect::<Vec<_>>().join("x");
+        Ok(if fits(stock)? {
+            format!(", Fits {}", size)
+        } else {
+            format!(", Does not fit {}", size)
+        })
+    }
+}
+
+impl Default for MeasureCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse "WxH" or "WxHxD"
+fn parse_stock(input: &str) -> Option<Vec<f64>> {
+    let values = input
+        .split(['x', 'X', '*'])
+        .map(|part| part.trim().parse::<f64>().ok().filter(|v| *v > 0.0))
+        .collect::<Option<Vec<_>>>()?;
+    (2..=3).contains(&values.len()).then_some(values)
+}
+
+/// Points of the selected entities, and whether any of them is 3D
+fn selection_points(context: &CommandContext) -> CommandResult<(Vec<Point3<f64>>, bool)> {
+    let flat = |points: Vec<Point2D>| points.into_iter().map(|p| Point3::new(p.x, p.y, 0.0));
+    let mut points = Vec::new();
+    let mut solid = false;
+    for id in &context.selection.entities {
+        let entity = context
+            .document
+            .get_entity(id)
+            .ok_or_else(|| CommandError::EntityNotFound(format!("Entity {:?} not found", id)))?;
+        if let Some(point) = entity.downcast_ref::<Point2D>() {
+            points.extend(flat(vec![*point]));
+        } else if let Some(line) = entity.downcast_ref::<LineSegment2D>() {
+            points.extend(flat(vec![line.start, line.end]));
+        } else if let Some(polyline) = entity.downcast_ref::<Polyline2D>() {
+            points.extend(flat(polyline.vertices.clone()));
+        } else if let Some(arc) = entity.downcast_ref::<Arc2D>() {
+            points.extend(flat(flatten(arc, MEASURE_FLATTEN_TOLERANCE)));
+        } else if let Some(circle) = entity.downcast_ref::<Circle2D>() {
+            points.extend(flat(flatten(circle, MEASURE_FLATTEN_TOLERANCE)));
+        } else if let Some(ellipse) = entity.downcast_ref::<Ellipse2D>() {
+            points.extend(flat(flatten(ellipse, MEASURE_FLATTEN_TOLERANCE)));
+        } else if let Some(arc) = entity.downcast_ref::<EllipticalArc2D>() {
+            points.extend(flat(flatten(arc, MEASURE_FLATTEN_TOLERANCE)));
+        } else if let Some(spline) = entity.downcast_ref::<BSpline>() {
+            points.extend(flat(flatten(spline, MEASURE_FLATTEN_TOLERANCE)));
+        } else if let Some(nurbs) = entity.downcast_ref::<NurbsCurve>() {
+            points.extend(flat(flatten(nurbs, MEASURE_FLATTEN_TOLERANCE)));
+        } else if let Some(polyline) = entity.downcast_ref::<ArcPolyline>() {
+            for segment in polyline.segments() {
+                match segment {
+                    FitSegment::Line(line) => points.extend(flat(vec![line.start, line.end])),
+                    FitSegment::Arc(arc) => points.extend(flat(flatten(&arc, MEASURE_FLATTEN_TOLERANCE))),
+                }
+            }
+        } else if let Some(mesh) = entity.downcast_ref::<TriangleMesh>() {
+            points.extend(mesh.vertices.iter().map(|v| v.position));
+            solid = true;
+        } else if let Some(cloud) = entity.downcast_ref::<PointCloud>() {
+            points.extend(cloud.points());
+            solid = true;
+        } else {
+            return Err(CommandError::InvalidSelection(format!("Entity {:?} cannot be measured", id)));
+        }
+    }
+    if points.is_empty() {
+        return Err(CommandError::InvalidSelection("Nothing selected to measure".to_string()));
+    }
+    Ok((points, solid))
+}
+
+impl Command for MeasureCommand {
+    fn name(&self) -> &str {
+        "MEASURE"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["MEA"]
+    }
+
+    fn description(&self) -> &str {
+        "Report the smallest oriented box, circle or sphere around the selection"
+    }
+
+    fn usage(&self) -> &str {
+        "MEASURE [BOX|CIRCLE|SPHERE] [WxH[xD]] (select entities)"
+    }
+
+    fn execute(&mut self, context: &mut CommandContext) -> CommandResult {
+        let (points, solid) = selection_points(context)?;
+        let readout = &context.readout;
+        let distance = |value: f64| readout.format_distance(value);
+        let failed = || CommandError::GeometricError("Could not enclose the selection".to_string());
+
+        let report = match self.shape {
+            MeasureShape::Box if solid => {
+                let obb = min_volume_box(&points).ok_or_else(failed)?;
+                let [length, width, height] = obb.extents;
+                let fit = self.fit_text(|stock| match *stock {
+                    [a, b, c] => Ok(obb.fits_within([a, b, c])),
+                    _ => Err(CommandError::InvalidInput("3D stock size must be LxWxH".to_string())),
+                })?;
+                format!(
+                    "Length = {}, Width = {}, Height = {}, Volume = {:.*}{}",
+                    distance(length),
+                    distance(width),
+                    distance(height),
+                    readout.precision,
+                    obb.volume(),
+                    fit,
+                )
+            }
+            MeasureShape::Box => {
+                let footprint: Vec<Point2D> = points.iter().map(|p| Point2D::new(p.x, p.y)).collect();
+                let rect = min_area_rect(&footprint).ok_or_else(failed)?;
+                let fit = self.fit_text(|stock| match *stock {
+                    [w, h] => Ok(rect.fits_within(w, h)),
+                    _ => Err(CommandError::InvalidInput("2D stock size must be WxH".to_string())),
+                })?;
+                format!(
+                    "Width = {}, Height = {}, Angle = {}, Area = {:.*}{}",
+                    distance(rect.width),
+                    distance(rect.height),
+                    readout.format_direction(rect.angle()),
+                    readout.precision,
+                    rect.area(),
+                    fit,
+                )
+            }
+            MeasureShape::Circle => {
+                let footprint: Vec<Point2D> = points.iter().map(|p| Point2D::new(p.x, p.y)).collect();
+                let circle = enclosing_circle(&footprint).ok_or_else(failed)?;
+                let diameter = 2.0 * circle.radius;
+                let fit = self.fit_text(|stock| Ok(diameter <= stock[0].min(stock[1]) + EPSILON))?;
+                format!(
+                    "Center = {}, {}, Diameter = {}{}",
+                    distance(circle.center.x),
+                    distance(circle.center.y),
+                    distance(diameter),
+                    fit,
+                )
+            }
+            MeasureShape::Sphere => {
+                let sphere = enclosing_sphere(&points).ok_or_else(failed)?;
+                let diameter = 2.0 * sphere.radius;
+                let fit = self.fit_text(|stock| {
+                    Ok(diameter <= stock.iter().copied().fold(f64::INFINITY, f64::min) + EPSILON)
+                })?;
+                format!(
+                    "Center = {}, {}, {}, Diameter = {}{}",
+                    distance(sphere.center.x),
+                    distance(sphere.center.y),
+                    distance(sphere.center.z),
+                    distance(diameter),
+                    fit,
+                )
+            }
+        };
+        self.report = Some(report);
+
+        self.state = CommandState::Completed;
+        Ok(())
+    }
+
+    fn undo(&mut self, _context: &mut CommandContext) -> CommandResult {
+        Ok(())
+    }
+
+    fn can_undo(&self) -> bool {
+        false
+    }
+
+    fn state(&self) -> CommandState {
+        self.state.clone()
+    }
+
+    fn process_input(&mut self, input: &str, _context: &mut CommandContext) -> CommandResult {
+        for token in input.split_whitespace() {
+            match token.to_ascii_uppercase().as_str() {
+                "BOX" | "B" => self.shape = MeasureShape::Box,
+                "CIRCLE" | "C" => self.shape = MeasureShape::Circle,
+                "SPHERE" | "S" => self.shape = MeasureShape::Sphere,
+                _ => {
+                    self.stock = Some(parse_stock(token).ok_or_else(|| {
+                        CommandError::InvalidInput(format!("Expected BOX, CIRCLE, SPHERE or a stock size: {}", token))
+                    })?)
+                }
+            }
+        }
+        self.state = CommandState::Executing;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// ==================== MEASUREONSURFACE COMMAND ====================
+
+/// Shortest distance between two points along a selected mesh's surface,
+/// the length a cable or pipe laid on it would need
+#[derive(Clone)]
+pub struct MeasureOnSurfaceCommand {
+    first: Option<Point>,
+    second: Option<Point>,
+    path: Option<GeodesicPath>,
+    report: Option<String>,
+    state: CommandState,
+}
+
+impl MeasureOnSurfaceCommand {
+    pub fn new() -> Self {
+        Self {
+            first: None,
+            second: None,
+            path: None,
+            report: None,
+            state: CommandState::AwaitingParameter("first point".to_string()),
+        }
+    }
+
+    /// Measurement text from the last run
+    pub fn report(&self) -> Option<&str> {
+        self.report.as_deref()
+    }
+
+    /// Route along the surface from the last run
+    pub fn path(&self) -> Option<&GeodesicPath> {
+        self.path.as_ref()
+    }
+}
+
+impl Default for MeasureOnSurfaceCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Command for MeasureOnSurfaceCommand {
+    fn name(&self) -> &str {
+        "MEASUREONSURFACE"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["MOS"]
+    }
+
+    fn description(&self) -> &str {
+        "Measure the shortest route between two points along a mesh surface"
+    }
+
+    fn usage(&self) -> &str {
+        "MEASUREONSURFACE <x1,y1,z1> <x2,y2,z2> (select a mesh)"
+    }
+
+    fn execute(&mut self, context: &mut CommandContext) -> CommandResult {
+        let first = self.first.ok_or_else(||
+            CommandError::InvalidInput("First point not specified".to_string()))?;
+        let second = self.second.ok_or_else(||
+            CommandError::InvalidInput("Second point not specified".to_string()))?;
+
+        let mesh = context
+            .selection
+            .entities
+            .iter()
+            .find_map(|id| context.document.get_entity(id)?.downcast_ref::<TriangleMesh>())
+            .ok_or_else(|| CommandError::InvalidSelection("Select a mesh to measure on".to_string()))?;
+        let positions = mesh.vertices.iter().map(|v| v.position).collect();
+        let triangles = mesh.faces.iter().map(|f| f.vertices).collect();
+        let geometric = |e: GeodesicError| CommandError::GeometricError(e.to_string());
+        let solver = GeodesicSolver::from_triangles(positions, triangles, GeodesicSettings::default())
+            .map_err(geometric)?;
+
+        let (from, to) = (Point3::new(first.x, first.y, first.z), Point3::new(second.x, second.y, second.z));
+        let path = solver.shortest_path_between(&from, &to).map_err(geometric)?;
+
+        let readout = &context.readout;
+        let (start, end) = (path.points[0], path.points[path.points.len() - 1]);
+        self.report = Some(format!(
+            "Surface Distance = {}, Direct Distance = {}, Path Points = {}",
+            readout.format_distance(path.length),
+            readout.format_distance((end - start).norm()),
+            path.points.len(),
+        ));
+        self.path = Some(path);
+
+        self.state = CommandState::Completed;
+        Ok(())
+    }
+
+    fn undo(&mut self, _context: &mut CommandContext) -> CommandResult {
+        Ok(())
+    }
+
+    fn can_undo(&self) -> bool {
+        false
+    }
+
+    fn state(&self) -> CommandState {
+        self.state.clone()
+    }
+
+    fn process_input(&mut self, input: &str, _context: &mut CommandContext) -> CommandResult {
+        let point = parse_point(input)?;
+        if self.first.is_none() {
+            self.first = Some(point);
+            self.state = CommandState::AwaitingParameter("second point".to_string());
+        } else {
+            self.second = Some(point);
+            self.state = CommandState::Executing;
+        }
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// ==================== MASSPROP COMMAND ====================
+
+/// Volume, centroid and inertia of selected meshes, or area, centroid and
+/// second moments of area of selected closed curves
+#[derive(Clone)]
+pub struct MassPropCommand {
+    density: f64,
+    thickness: Option<f64>,
+    report: Option<String>,
+    state: CommandState,
+}
+
+impl MassPropCommand {
+    pub fn new() -> Self {
+        Self {
+            density: 1.0,
+            thickness: None,
+            report: None,
+            state: CommandState::AwaitingParameter("density and plate thickness, or Enter".to_string()),
+        }
+    }
+
+    /// Report text from the last run, one line per selected entity
+    pub fn report(&self) -> Option<&str> {
+        self.report.as_deref()
+    }
+}
+
+impl Default for MassPropCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Outline of a closed 2D entity, flattened for area integration
+fn closed_outline(entity: &dyn Any) -> Option<Polygon2D> {
+    let ring = |points: Vec<Point2D>| Some(Polygon2D::new(points));
+    if let Some(circle) = entity.downcast_ref::<Circle2D>() {
+        ring(flatten(circle, MEASURE_FLATTEN_TOLERANCE))
+    } else if let Some(ellipse) = entity.downcast_ref::<Ellipse2D>() {
+        ring(flatten(ellipse, MEASURE_FLATTEN_TOLERANCE))
+    } else if let Some(polyline) = entity.downcast_ref::<Polyline2D>() {
+        polyline.closed.then(|| Polygon2D::new(polyline.vertices.clone()))
+    } else if let Some(polyline) = entity.downcast_ref::<ArcPolyline>() {
+        let mut points = Vec::new();
+        for segment in polyline.segments() {
+            match segment {
+                FitSegment::Line(line) => points.push(line.start),
+                FitSegment::Arc(arc) => {
+                    let mut arc_points = flatten(&arc, MEASURE_FLATTEN_TOLERANCE);
+                    arc_points.pop();
+                    points.extend(arc_points);
+                }
+            }
+        }
+        polyline.closed.then(|| Polygon2D::new(points))
+    } else if let Some(polygon) = entity.downcast_ref::<Polygon2D>() {
+        Some(polygon.clone())
+    } else {
+        entity
+            .downcast_ref::<Region>()
+            .map(|region| region.to_polygon(MEASURE_FLATTEN_TOLERANCE))
+    }
+}
+
+impl Command for MassPropCommand {
+    fn name(&self) -> &str {
+        "MASSPROP"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["MP"]
+    }
+
+    fn description(&self) -> &str {
+        "Report mass properties of selected solids and closed regions"
+    }
+
+    fn usage(&self) -> &str {
+        "MASSPROP [density] [plate thickness] (select meshes or closed curves)"
+    }
+
+    fn execute(&mut self, context: &mut CommandContext) -> CommandResult {
+        if context.selection.entities.is_empty() {
+            return Err(CommandError::InvalidSelection("Nothing selected".to_string()));
+        }
+        let readout = &context.readout;
+        let distance = |value: f64| readout.format_distance(value);
+        let number = |value: f64| format!("{:.*}", readout.precision, value);
+
+        let mut lines = Vec::new();
+        for id in &context.selection.entities {
+            let entity = context
+                .document
+                .get_entity(id)
+                .ok_or_else(|| CommandError::EntityNotFound(format!("Entity {:?} not found", id)))?;
+
+            if let Some(mesh) = entity.downcast_ref::<TriangleMesh>() {
+                let props = mesh.mass_properties(self.density).ok_or_else(|| {
+                    CommandError::GeometricError(format!("Entity {:?} encloses no volume", id))
+                })?;
+                let (c, i) = (props.centroid, props.inertia);
+                let radii = props.radii_of_gyration();
+                lines.push(format!(
+                    "Mass = {}, Volume = {}, Surface Area = {}, Centroid = {}, {}, {}, \
+                     Moments of Inertia = {}, {}, {}, Products of Inertia = {}, {}, {}, \
+                     Principal Moments = {}, {}, {}, Radii of Gyration = {}, {}, {}",
+                    number(props.mass),
+                    number(props.volume),
+                    number(props.surface_area),
+                    distance(c.x),
+                    distance(c.y),
+                    distance(c.z),
+                    number(i[(0, 0)]),
+                    number(i[(1, 1)]),
+                    number(i[(2, 2)]),
+                    number(-i[(0, 1)]),
+                    number(-i[(1, 2)]),
+                    number(-i[(2, 0)]),
+                    number(props.principal_moments[0]),
+                    number(props.principal_moments[1]),
+                    number(props.principal_moments[2]),
+                    distance(radii[0]),
+                    distance(radii[1]),
+                    distance(radii[2]),
+                ));
+            } else if let Some(outline) = closed_outline(entity.as_ref()) {
+                let props = AreaProperties::of_polygon(&outline).ok_or_else(|| {
+                    CommandError::GeometricError(format!("Entity {:?} encloses no area", id))
+                })?;
+                let (major, minor, angle) = props.principal_moments();
+                let (kx, ky) = props.radii_of_gyration();
+                let mass = self
+                    .thickness
+                    .map(|t| format!(", Mass = {}", number(props.mass(self.density * t))))
+                    .unwrap_or_default();
+                lines.push(format!(
+                    "Area = {}, Perimeter = {}, Centroid = {}, {}, Moments of Area = {}, {}, \
+                     Product of Area = {}, Principal Moments = {}, {} at {}, Radii of Gyration = {}, {}{}",
+                    number(props.area),
+                    distance(props.perimeter),
+                    distance(props.centroid.x),
+                    distance(props.centroid.y),
+                    number(props.ixx),
+                    number(props.iyy),
+                    number(props.ixy),
+                    number(major),
+                    number(minor),
+                    readout.format_angle(angle),
+                    distance(kx),
+                    distance(ky),
+                    mass,
+                ));
+            } else {
+                return Err(CommandError::InvalidSelection(format!(
+                    "Entity {:?} is not a solid or closed region",
+                    id
+                )));
+            }
+        }
+        self.report = Some(lines.join("\n"));
+
+        self.state = CommandState::Completed;
+        Ok(())
+    }
+
+    fn undo(&mut self, _context: &mut CommandContext) -> CommandResult {
+        Ok(())
+    }
+
+    fn can_undo(&self) -> bool {
+        false
+    }
+
+    fn state(&self) -> CommandState {
+        self.state.clone()
+    }
+
+    fn process_input(&mut self, input: &str, _context: &mut CommandContext) -> CommandResult {
+        let values = input
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.parse::<f64>().ok().filter(|v| *v > 0.0))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| CommandError::InvalidInput(format!("Expected density and thickness: {}", input)))?;
+        match values.as_slice() {
+            [] => {}
+            [density] => self.density = *density,
+            [density, thickness] => {
+                self.density = *density;
+                self.thickness = Some(*thickness);
+            }
+            _ => return Err(CommandError::InvalidInput(format!("Expected density and thickness: {}", input))),
+        }
+        self.state = CommandState::Executing;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
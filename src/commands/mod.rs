@@ -9,23 +9,38 @@ pub mod draw;
 pub mod modify;
 pub mod edit;
 pub mod view;
+pub mod inquiry;
+pub mod session;
+pub mod documents;
+pub mod features;
+pub mod blend;
+pub mod direct;
 
 // Re-export commonly used types
 pub use command::{
     Command, CommandContext, CommandError, CommandResult, CommandState,
     CommandInput, CommandParameter, CommandMemento,
-    Point, EntityId, SelectionSet, Document,
+    Point, EntityId, EntityRevision, EntityChange, ChangeSet, SelectionSet, Document,
 };
 
 pub use history::{UndoStack, HistoryConfig};
 pub use registry::CommandRegistry;
 pub use processor::{CommandProcessor, InputParser};
+pub use documents::{DocumentSet, DocumentSetError, DocumentSetResult, OpenDocument};
+pub use session::{
+    RecordedEvent, ReplayReport, ReplayStep, SessionError, SessionEvent, SessionHeader,
+    SessionPlayer, SessionRecorder, SessionRecording, SessionResult, ViewportState,
+};
 
 // Re-export all command implementations
 pub use draw::*;
 pub use modify::*;
 pub use edit::*;
 pub use view::*;
+pub use inquiry::*;
+pub use features::{FeatureCommand, FeatureEdit};
+pub use blend::{ChamferEdgeCommand, FilletEdgeCommand};
+pub use direct::PushPullCommand;
 
 /// Initialize and register all standard CAD commands
 pub fn register_all_commands(registry: &mut CommandRegistry) {
@@ -55,6 +70,9 @@ pub fn register_all_commands(registry: &mut CommandRegistry) {
     registry.register_with_category(Box::new(BreakCommand::new()), "Modify");
     registry.register_with_category(Box::new(JoinCommand::new()), "Modify");
     registry.register_with_category(Box::new(ExplodeCommand::new()), "Modify");
+    registry.register_with_category(Box::new(FitArcsCommand::new()), "Modify");
+    registry.register_with_category(Box::new(SimplifySplineCommand::new()), "Modify");
+    registry.register_with_category(Box::new(SplineToArcsCommand::new()), "Modify");
 
     // Edit commands
     registry.register_with_category(Box::new(EraseCommand::new()), "Edit");
@@ -73,6 +91,19 @@ pub fn register_all_commands(registry: &mut CommandRegistry) {
     registry.register_with_category(Box::new(RedrawCommand::new()), "View");
     registry.register_with_category(Box::new(ViewCommand::new()), "View");
     registry.register_with_category(Box::new(ViewResCommand::new()), "View");
+
+    // Inquiry commands
+    registry.register_with_category(Box::new(DistCommand::new()), "Inquiry");
+    registry.register_with_category(Box::new(IdCommand::new()), "Inquiry");
+    registry.register_with_category(Box::new(MeasureCommand::new()), "Inquiry");
+    registry.register_with_category(Box::new(MeasureOnSurfaceCommand::new()), "Inquiry");
+    registry.register_with_category(Box::new(MassPropCommand::new()), "Inquiry");
+
+    // Modeling commands
+    registry.register_with_category(Box::new(FeatureCommand::new()), "Model");
+    registry.register_with_category(Box::new(FilletEdgeCommand::new()), "Model");
+    registry.register_with_category(Box::new(ChamferEdgeCommand::new()), "Model");
+    registry.register_with_category(Box::new(PushPullCommand::new()), "Model");
 }
 
 /// Create a fully initialized command processor with all standard commands
@@ -206,7 +237,7 @@ mod tests {
 
     #[test]
     fn test_input_parser() {
-        let mut parser = InputParser::new("10 20 30");
+        let mut parser = InputParser::new("10,20,30");
         let point = parser.parse_point();
         assert!(point.is_ok());
         let p = point.unwrap();
@@ -215,6 +246,25 @@ mod tests {
         assert_eq!(p.z, 30.0);
     }
 
+    #[test]
+    fn test_input_parser_expressions() {
+        use crate::io::parameters::Parameters;
+        use crate::io::units::Unit;
+
+        let mut params = Parameters::new();
+        params.set("width", "2ft", Some(Unit::Inches)).unwrap();
+        let values = params.evaluate(Unit::Millimeters).unwrap();
+        let input = r#""25.4mm*3 + 1in",width/2,sqrt(2)*10 "360/8" 12/4 x"#;
+        let mut parser = InputParser::new(input).with_values(values);
+        let p = parser.parse_point().unwrap();
+        assert!((p.x - 101.6).abs() < 1e-9);
+        assert!((p.y - 304.8).abs() < 1e-9);
+        assert!((p.z - 2f64.sqrt() * 10.0).abs() < 1e-9);
+        assert_eq!(parser.parse_angle().unwrap(), 45.0);
+        assert_eq!(parser.parse_integer().unwrap(), 3);
+        assert!(matches!(parser.parse_distance(), Err(CommandError::InvalidInput(_))));
+    }
+
     #[test]
     fn test_input_parser_quoted_strings() {
         let parser = InputParser::new(r#"TEXT "Hello World" 10 20"#);
@@ -277,4 +327,291 @@ mod tests {
         let processor = create_processor_with_config(config);
         assert_eq!(processor.history().config().max_undo_levels, 5);
     }
+
+    #[test]
+    fn test_entity_revisions() {
+        let mut doc = Document::new();
+        let a = doc.add_entity(Box::new(1u32));
+        let b = doc.add_entity(Box::new(2u32));
+        assert_eq!(doc.version(), 2);
+        let seen = doc.version();
+
+        assert!(doc.get_entity_mut(&a).is_some());
+        assert!(doc.touch_entity(&a));
+        doc.remove_entity(&b);
+        assert!(!doc.touch_entity(&b));
+
+        let rev = doc.entity_revision(&a).unwrap();
+        assert_eq!(rev.revision, 3);
+        assert_eq!(rev.version, 4);
+        assert!(rev.modified_by.is_none());
+
+        let changes = doc.changes_since(seen);
+        let ids: Vec<EntityId> = changes.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![a, b]);
+        assert!(changes[1].1.deleted);
+
+        // Restoring keeps counting from the removal
+        doc.insert_entity(b, Box::new(3u32));
+        let rev = doc.entity_revision(&b).unwrap();
+        assert_eq!(rev.revision, 3);
+        assert!(!rev.deleted);
+        assert!(doc.changes_since(doc.version()).is_empty());
+
+        doc.remove_entity(&b);
+        doc.prune_deleted(doc.version());
+        assert!(doc.entity_revision(&b).is_none());
+        assert!(doc.entity_revision(&a).is_some());
+        assert_ne!(doc.add_entity(Box::new(4u32)), b);
+    }
+
+    #[test]
+    fn test_processor_records_change_source() {
+        let mut processor = create_standard_processor();
+        let mut context = CommandContext::new(Document::new());
+
+        processor.queue_command(Box::new(LineCommand::with_points(
+            Point::origin(),
+            Point::new_2d(10.0, 0.0),
+        )));
+        processor.execute_queue(&mut context).unwrap();
+
+        let changes = context.document.changes_since(0);
+        assert_eq!(changes.len(), 1);
+        let (id, rev) = changes[0];
+        assert_eq!(rev.modified_by.as_deref(), Some("LINE"));
+
+        let seen = context.document.version();
+        processor.undo(&mut context).unwrap();
+        let rev = context.document.entity_revision(&id).unwrap();
+        assert!(rev.deleted);
+        assert_eq!(rev.revision, 2);
+        assert_eq!(rev.modified_by.as_deref(), Some("UNDO"));
+        assert_eq!(context.document.changes_since(seen).len(), 1);
+    }
+
+    #[test]
+    fn test_curve_fitting_commands() {
+        use crate::geometry::{ArcPolyline, BSpline, Point2D, Polyline2D};
+
+        let mut document = Document::new();
+        let arc_points = (0..=24)
+            .map(|i| Point2D::from_polar(5.0, i as f64 * std::f64::consts::PI / 24.0))
+            .collect();
+        let polyline = document.add_entity(Box::new(Polyline2D::open(arc_points)));
+        let control_points = (0..30)
+            .map(|i| Point2D::new(i as f64, (i as f64 * 0.3).sin()))
+            .collect();
+        let spline = document.add_entity(Box::new(BSpline::clamped(control_points, 3).unwrap()));
+
+        let mut context = CommandContext::new(document)
+            .with_selection(SelectionSet::from_entities(vec![polyline, spline]))
+            .with_option("tolerance", "0.02");
+
+        let mut fit = FitArcsCommand::new();
+        fit.execute(&mut context).unwrap();
+        let fitted = context.document.get_entity(&polyline).unwrap();
+        assert_eq!(fitted.downcast_ref::<ArcPolyline>().unwrap().arc_count(), 1);
+
+        let mut simplify = SimplifySplineCommand::new();
+        simplify.execute(&mut context).unwrap();
+        let simplified = context.document.get_entity(&spline).unwrap();
+        assert!(simplified.downcast_ref::<BSpline>().unwrap().control_points.len() < 30);
+
+        let mut to_arcs = SplineToArcsCommand::new();
+        to_arcs.process_input("0.05", &mut context).unwrap();
+        to_arcs.execute(&mut context).unwrap();
+        assert!(context.document.get_entity(&spline).unwrap().is::<ArcPolyline>());
+
+        to_arcs.undo(&mut context).unwrap();
+        simplify.undo(&mut context).unwrap();
+        fit.undo(&mut context).unwrap();
+        let restored = context.document.get_entity(&spline).unwrap();
+        assert_eq!(restored.downcast_ref::<BSpline>().unwrap().control_points.len(), 30);
+        assert!(context.document.get_entity(&polyline).unwrap().is::<Polyline2D>());
+    }
+
+    #[test]
+    fn test_fillet_and_chamfer_commands() {
+        use crate::geometry::{Arc2D, LineSegment2D, Point2D};
+
+        let mut document = Document::new();
+        let a = document.add_entity(Box::new(LineSegment2D::new(Point2D::new(0.0, 0.0), Point2D::new(10.0, 0.0))));
+        let b = document.add_entity(Box::new(LineSegment2D::new(Point2D::new(0.0, 0.0), Point2D::new(0.0, 10.0))));
+        let mut context = CommandContext::new(document)
+            .with_selection(SelectionSet::from_entities(vec![a, b]));
+
+        let mut fillet = FilletCommand::new();
+        fillet.process_input("2", &mut context).unwrap();
+        fillet.execute(&mut context).unwrap();
+        assert_eq!(context.document.entity_count(), 3);
+        let trimmed = context.document.get_entity(&a).unwrap().downcast_ref::<LineSegment2D>().unwrap();
+        assert_eq!(trimmed.start, Point2D::new(2.0, 0.0));
+        assert!(context.document.entities.values().any(|e| e.is::<Arc2D>()));
+
+        fillet.undo(&mut context).unwrap();
+        assert_eq!(context.document.entity_count(), 2);
+        let restored = context.document.get_entity(&a).unwrap().downcast_ref::<LineSegment2D>().unwrap();
+        assert_eq!(restored.start, Point2D::new(0.0, 0.0));
+
+        let mut chamfer = ChamferCommand::new();
+        chamfer.process_input("2,3", &mut context).unwrap();
+        chamfer.execute(&mut context).unwrap();
+        let trimmed = context.document.get_entity(&b).unwrap().downcast_ref::<LineSegment2D>().unwrap();
+        assert_eq!(trimmed.start, Point2D::new(0.0, 3.0));
+
+        let mut too_big = FilletCommand::new();
+        too_big.process_input("50", &mut context).unwrap();
+        assert!(matches!(too_big.execute(&mut context), Err(CommandError::GeometricError(_))));
+    }
+
+    #[test]
+    fn test_inquiry_readouts() {
+        use crate::geometry::Point2D;
+        use crate::io::readout::{Alignment, AngleFormat, CoordinateMode, ReadoutFormat};
+
+        let readout = ReadoutFormat {
+            angle: AngleFormat::Bearing,
+            angle_precision: 2,
+            precision: 2,
+            coordinates: CoordinateMode::StationOffset,
+            ..ReadoutFormat::default()
+        };
+        let alignment = Alignment::new(vec![Point2D::new(0.0, 0.0), Point2D::new(2000.0, 0.0)], 0.0);
+        let mut context = CommandContext::new(Document::new())
+            .with_readout(readout)
+            .with_alignment(alignment);
+
+        let mut dist = DistCommand::new();
+        dist.process_input("0,0", &mut context).unwrap();
+        dist.process_input("3 4", &mut context).unwrap();
+        dist.execute(&mut context).unwrap();
+        let report = dist.report().unwrap();
+        assert!(report.starts_with("Distance = 5.00, Angle in XY Plane = N36°52'E"), "{}", report);
+        assert!(!dist.can_undo());
+
+        let mut id = IdCommand::new();
+        id.process_input("1234.567,-5", &mut context).unwrap();
+        id.execute(&mut context).unwrap();
+        assert!(id.report().unwrap().starts_with("12+34.57, 5.00 R"), "{}", id.report().unwrap());
+    }
+
+    #[test]
+    fn test_measure_command() {
+        use crate::geometry::{Point2D, Polyline2D};
+        use crate::io::readout::ReadoutFormat;
+
+        // A 4 x 2 outline turned 30°
+        let (c, s) = (30f64.to_radians().cos(), 30f64.to_radians().sin());
+        let corners = [(-2.0, -1.0), (2.0, -1.0), (2.0, 1.0), (-2.0, 1.0)]
+            .map(|(x, y)| Point2D::new(x * c - y * s, x * s + y * c));
+        let mut document = Document::new();
+        let outline = document.add_entity(Box::new(Polyline2D::new(corners.to_vec(), true)));
+        let mut context = CommandContext::new(document)
+            .with_selection(SelectionSet::from_entities(vec![outline]))
+            .with_readout(ReadoutFormat { precision: 2, ..ReadoutFormat::default() });
+
+        let mut measure = MeasureCommand::new();
+        measure.process_input("3x5", &mut context).unwrap();
+        measure.execute(&mut context).unwrap();
+        let report = measure.report().unwrap();
+        assert!(report.starts_with("Width = 4.00, Height = 2.00"), "{}", report);
+        assert!(report.ends_with("Area = 8.00, Fits 3x5"), "{}", report);
+
+        let mut circle = MeasureCommand::new();
+        circle.process_input("circle 4x4", &mut context).unwrap();
+        circle.execute(&mut context).unwrap();
+        let report = circle.report().unwrap();
+        assert!(report.contains("Diameter = 4.47, Does not fit 4x4"), "{}", report);
+
+        assert!(MeasureCommand::new().process_input("cube", &mut context).is_err());
+        context.selection = SelectionSet::new();
+        assert!(MeasureCommand::new().execute(&mut context).is_err());
+    }
+
+    #[test]
+    fn test_massprop_command() {
+        use crate::geometry::{Point2D, Polyline2D, QuadFace, QuadMesh, Vertex};
+        use crate::io::readout::ReadoutFormat;
+        use nalgebra::Point3;
+
+        // A 4 x 2 plate and a 2 x 2 x 2 cube with outward faces
+        let corners = [(0.0, 0.0), (4.0, 0.0), (4.0, 2.0), (0.0, 2.0)].map(|(x, y)| Point2D::new(x, y));
+        let mut cube = QuadMesh::new();
+        for i in 0..8 {
+            let (x, y, z) = ((i & 1) as f64, ((i >> 1) & 1) as f64, (i >> 2) as f64);
+            cube.vertices.push(Vertex::new(Point3::new(2.0 * x, 2.0 * y, 2.0 * z)));
+        }
+        let sides = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+        cube.faces.extend(sides.map(|[a, b, c, d]| QuadFace::new(a, b, c, d)));
+        let mut document = Document::new();
+        let plate = document.add_entity(Box::new(Polyline2D::new(corners.to_vec(), true)));
+        let solid = document.add_entity(Box::new(cube.to_triangle_mesh()));
+        let mut context = CommandContext::new(document)
+            .with_selection(SelectionSet::from_entities(vec![plate, solid]))
+            .with_readout(ReadoutFormat { precision: 2, ..ReadoutFormat::default() });
+
+        let mut massprop = MassPropCommand::new();
+        massprop.process_input("7.85, 0.5", &mut context).unwrap();
+        massprop.execute(&mut context).unwrap();
+        let report = massprop.report().unwrap();
+        let lines: Vec<_> = report.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("Area = 8.00, Perimeter = 12.00, Centroid = 2.00, 1.00"), "{}", report);
+        assert!(lines[0].ends_with("Mass = 31.40"), "{}", report);
+        assert!(lines[1].starts_with("Mass = 62.80, Volume = 8.00, Surface Area = 24.00"), "{}", report);
+        assert!(lines[1].contains("Centroid = 1.00, 1.00, 1.00"), "{}", report);
+
+        assert!(MassPropCommand::new().process_input("-1", &mut context).is_err());
+        let open = context.document.add_entity(Box::new(Polyline2D::new(corners.to_vec(), false)));
+        context.selection = SelectionSet::from_entities(vec![open]);
+        assert!(MassPropCommand::new().execute(&mut context).is_err());
+    }
+
+    #[test]
+    fn test_measure_on_surface_command() {
+        use crate::geometry::{TriangleFace, TriangleMesh, Vertex};
+        use crate::io::readout::ReadoutFormat;
+        use nalgebra::Point3;
+
+        // A 1 x 1 floor folded up into a 1 x 1 wall along y = 1
+        let n = 10;
+        let at = |i: usize, k: usize| {
+            let (x, s) = (i as f64 / n as f64, k as f64 / n as f64);
+            if s <= 1.0 { Point3::new(x, s, 0.0) } else { Point3::new(x, 1.0, s - 1.0) }
+        };
+        let mut mesh = TriangleMesh::new();
+        for k in 0..=2 * n {
+            for i in 0..=n {
+                mesh.add_vertex(Vertex::new(at(i, k)));
+            }
+        }
+        let index = |i: usize, k: usize| k * (n + 1) + i;
+        for k in 0..2 * n {
+            for i in 0..n {
+                mesh.add_face(TriangleFace::new(index(i, k), index(i + 1, k), index(i + 1, k + 1)));
+                mesh.add_face(TriangleFace::new(index(i, k), index(i + 1, k + 1), index(i, k + 1)));
+            }
+        }
+        let mut document = Document::new();
+        let id = document.add_entity(Box::new(mesh));
+        let mut context = CommandContext::new(document)
+            .with_selection(SelectionSet::from_entities(vec![id]))
+            .with_readout(ReadoutFormat { precision: 2, ..ReadoutFormat::default() });
+
+        let mut measure = MeasureOnSurfaceCommand::new();
+        measure.process_input("0.5,0.5,0", &mut context).unwrap();
+        measure.process_input("0.5,1,0.5", &mut context).unwrap();
+        measure.execute(&mut context).unwrap();
+        let report = measure.report().unwrap();
+        assert!(report.starts_with("Surface Distance = 1.00, Direct Distance = 0.71"), "{}", report);
+        assert!(measure.path().unwrap().points.iter().any(|p| (p.y - 1.0).abs() < 1e-9 && p.z.abs() < 1e-9));
+        assert!(!measure.can_undo());
+
+        context.selection = SelectionSet::new();
+        let mut unselected = MeasureOnSurfaceCommand::new();
+        unselected.process_input("0,0,0", &mut context).unwrap();
+        unselected.process_input("1,1,1", &mut context).unwrap();
+        assert!(matches!(unselected.execute(&mut context), Err(CommandError::InvalidSelection(_))));
+    }
 }
@@ -2,6 +2,9 @@
 // Implements entity modification commands (MOVE, COPY, ROTATE, SCALE, etc.)
 
 use super::command::*;
+use crate::geometry::fillet::{self, Corner};
+use crate::geometry::fitting::{self, FitSegment};
+use crate::geometry::{Arc2D, BSpline, LineSegment2D, NurbsCurve, Polyline2D};
 use std::any::Any;
 use std::collections::HashMap;
 
@@ -503,10 +506,20 @@ pub enum ArrayType {
     Polar { count: i32, angle: f64, center: Point },
 }
 
+/// Array count that can be taken from a parameter expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayCount {
+    Rows,
+    Columns,
+    /// Items of a polar array
+    Items,
+}
+
 #[derive(Clone)]
 pub struct ArrayCommand {
     selection: Vec<EntityId>,
     array_type: Option<ArrayType>,
+    count_expressions: Vec<(ArrayCount, String)>,
     created_entities: Vec<EntityId>,
     state: CommandState,
 }
@@ -516,10 +529,47 @@ impl ArrayCommand {
         Self {
             selection: Vec::new(),
             array_type: None,
+            count_expressions: Vec::new(),
             created_entities: Vec::new(),
             state: CommandState::AwaitingParameter("array type (R/P)".to_string()),
         }
     }
+
+    pub fn with_array_type(mut self, array_type: ArrayType) -> Self {
+        self.array_type = Some(array_type);
+        self
+    }
+
+    /// Take a count from a document parameter expression, e.g. `bays + 1`,
+    /// evaluated each time the array is made
+    pub fn with_count_expression(mut self, count: ArrayCount, expression: impl Into<String>) -> Self {
+        self.count_expressions.retain(|(c, _)| *c != count);
+        self.count_expressions.push((count, expression.into()));
+        self
+    }
+
+    /// Array type with its parametric counts evaluated
+    fn resolved_array_type(&self, context: &CommandContext) -> CommandResult<ArrayType> {
+        let mut array_type = self.array_type.clone().ok_or_else(||
+            CommandError::InvalidInput("Array type not specified".to_string()))?;
+        for (count, expression) in &self.count_expressions {
+            let value = context.parameters.count(expression)
+                .map_err(|e| CommandError::InvalidInput(e.to_string()))?;
+            let value = i32::try_from(value)
+                .map_err(|_| CommandError::ResourceLimit(format!("Array count {} is too large", value)))?;
+            match (count, &mut array_type) {
+                (ArrayCount::Rows, ArrayType::Rectangular { rows, .. }) => *rows = value,
+                (ArrayCount::Columns, ArrayType::Rectangular { columns, .. }) => *columns = value,
+                (ArrayCount::Items, ArrayType::Polar { count, .. }) => *count = value,
+                _ => {
+                    return Err(CommandError::InvalidInput(format!(
+                        "{:?} count does not apply to this array type", count
+                    )))
+                }
+            }
+        }
+        Ok(array_type)
+    }
 }
 
 impl Command for ArrayCommand {
@@ -549,8 +599,7 @@ impl Command for ArrayCommand {
             return Err(CommandError::InvalidSelection("No entities selected".to_string()));
         }
 
-        let array_type = self.array_type.as_ref().ok_or_else(||
-            CommandError::InvalidInput("Array type not specified".to_string()))?;
+        let array_type = self.resolved_array_type(context)?;
 
         // Create array based on type
         match array_type {
@@ -567,7 +616,7 @@ impl Command for ArrayCommand {
                 }
             }
             ArrayType::Polar { count, angle: _, center: _ } => {
-                for _ in 0..*count {
+                for _ in 0..count {
                     for entity_id in &self.selection {
                         if context.document.get_entity(entity_id).is_some() {
                             let copy_data = Box::new(());
@@ -797,14 +846,28 @@ impl Command for ExtendCommand {
     fn as_any(&self) -> &dyn Any { self }
 }
 
-#[derive(Clone)]
+/// FILLET: round the corner between two lines or arcs
 pub struct FilletCommand {
+    selection: Vec<EntityId>,
+    radius: Option<f64>,
+    edit: CornerEdit,
     state: CommandState,
 }
 
 impl FilletCommand {
     pub fn new() -> Self {
-        Self { state: CommandState::AwaitingInput }
+        Self {
+            selection: Vec::new(),
+            radius: None,
+            edit: CornerEdit::default(),
+            state: CommandState::AwaitingParameter("radius".to_string()),
+        }
+    }
+}
+
+impl Default for FilletCommand {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -813,21 +876,63 @@ impl Command for FilletCommand {
     fn aliases(&self) -> Vec<&str> { vec!["F"] }
     fn description(&self) -> &str { "Create rounded corner between two entities" }
     fn usage(&self) -> &str { "FILLET <radius> (select entities)" }
-    fn execute(&mut self, _context: &mut CommandContext) -> CommandResult { Ok(()) }
-    fn undo(&mut self, _context: &mut CommandContext) -> CommandResult { Ok(()) }
+
+    fn execute(&mut self, context: &mut CommandContext) -> CommandResult {
+        if self.selection.is_empty() {
+            self.selection = context.selection.entities.clone();
+        }
+        let radius = corner_distance(self.radius, context, "radius")?;
+        self.edit.apply(context, &self.selection, |a, b| fillet::fillet(a, b, radius))?;
+        self.state = CommandState::Completed;
+        Ok(())
+    }
+
+    fn undo(&mut self, context: &mut CommandContext) -> CommandResult {
+        self.edit.revert(context)
+    }
+
     fn state(&self) -> CommandState { self.state.clone() }
-    fn clone_box(&self) -> Box<dyn Command> { Box::new(self.clone()) }
+
+    fn process_input(&mut self, input: &str, _context: &mut CommandContext) -> CommandResult {
+        self.radius = Some(parse_distance(input)?);
+        self.state = CommandState::Executing;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(FilletCommand {
+            selection: self.selection.clone(),
+            radius: self.radius,
+            edit: CornerEdit::default(),
+            state: self.state.clone(),
+        })
+    }
+
     fn as_any(&self) -> &dyn Any { self }
 }
 
-#[derive(Clone)]
+/// CHAMFER: bevel the corner between two lines or arcs
 pub struct ChamferCommand {
+    selection: Vec<EntityId>,
+    distances: Option<(f64, f64)>,
+    edit: CornerEdit,
     state: CommandState,
 }
 
 impl ChamferCommand {
     pub fn new() -> Self {
-        Self { state: CommandState::AwaitingInput }
+        Self {
+            selection: Vec::new(),
+            distances: None,
+            edit: CornerEdit::default(),
+            state: CommandState::AwaitingParameter("distances".to_string()),
+        }
+    }
+}
+
+impl Default for ChamferCommand {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -836,13 +941,135 @@ impl Command for ChamferCommand {
     fn aliases(&self) -> Vec<&str> { vec!["CHA"] }
     fn description(&self) -> &str { "Create beveled corner between two entities" }
     fn usage(&self) -> &str { "CHAMFER <distance1> <distance2> (select entities)" }
-    fn execute(&mut self, _context: &mut CommandContext) -> CommandResult { Ok(()) }
-    fn undo(&mut self, _context: &mut CommandContext) -> CommandResult { Ok(()) }
+
+    fn execute(&mut self, context: &mut CommandContext) -> CommandResult {
+        if self.selection.is_empty() {
+            self.selection = context.selection.entities.clone();
+        }
+        let (first, second) = match self.distances {
+            Some(distances) => distances,
+            None => {
+                let first = corner_distance(None, context, "distance1")?;
+                let second = match context.get_option("distance2") {
+                    Some(value) => parse_distance(value)?,
+                    None => first,
+                };
+                (first, second)
+            }
+        };
+        self.edit.apply(context, &self.selection, |a, b| fillet::chamfer(a, b, first, second))?;
+        self.state = CommandState::Completed;
+        Ok(())
+    }
+
+    fn undo(&mut self, context: &mut CommandContext) -> CommandResult {
+        self.edit.revert(context)
+    }
+
     fn state(&self) -> CommandState { self.state.clone() }
-    fn clone_box(&self) -> Box<dyn Command> { Box::new(self.clone()) }
+
+    /// One distance bevels symmetrically; two set each side
+    fn process_input(&mut self, input: &str, _context: &mut CommandContext) -> CommandResult {
+        let values = input
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .map(parse_distance)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.distances = match values[..] {
+            [d] => Some((d, d)),
+            [d1, d2] => Some((d1, d2)),
+            _ => return Err(CommandError::InvalidInput(format!("Expected one or two distances: {}", input))),
+        };
+        self.state = CommandState::Executing;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(ChamferCommand {
+            selection: self.selection.clone(),
+            distances: self.distances,
+            edit: CornerEdit::default(),
+            state: self.state.clone(),
+        })
+    }
+
     fn as_any(&self) -> &dyn Any { self }
 }
 
+/// Entities replaced and created by a fillet or chamfer, for undo
+#[derive(Default)]
+struct CornerEdit {
+    originals: Vec<(EntityId, EntityData)>,
+    joint: Option<EntityId>,
+}
+
+impl CornerEdit {
+    /// Replace the two selected lines/arcs with their trimmed versions and
+    /// add the joint between them
+    fn apply<F>(&mut self, context: &mut CommandContext, selection: &[EntityId], corner: F) -> CommandResult
+    where
+        F: Fn(&FitSegment, &FitSegment) -> Option<Corner>,
+    {
+        let [a, b] = selection else {
+            return Err(CommandError::InvalidSelection("Select two lines or arcs".to_string()));
+        };
+        let corner = corner(&corner_operand(context, a)?, &corner_operand(context, b)?).ok_or_else(|| {
+            CommandError::GeometricError("No corner of that size between the entities".to_string())
+        })?;
+
+        for (id, segment) in [(*a, corner.first), (*b, corner.second)] {
+            if let Some(original) = context.document.insert_entity(id, segment_data(segment)) {
+                self.originals.push((id, original));
+            }
+        }
+        self.joint = corner.joint.map(|joint| context.document.add_entity(segment_data(joint)));
+        Ok(())
+    }
+
+    fn revert(&mut self, context: &mut CommandContext) -> CommandResult {
+        if let Some(joint) = self.joint.take() {
+            context.document.remove_entity(&joint);
+        }
+        restore_entities(context, &mut self.originals)
+    }
+}
+
+fn corner_operand(context: &CommandContext, id: &EntityId) -> CommandResult<FitSegment> {
+    let entity = context
+        .document
+        .get_entity(id)
+        .ok_or_else(|| CommandError::EntityNotFound(format!("Entity {:?} not found", id)))?;
+    if let Some(line) = entity.downcast_ref::<LineSegment2D>() {
+        Ok(FitSegment::Line(*line))
+    } else if let Some(arc) = entity.downcast_ref::<Arc2D>() {
+        Ok(FitSegment::Arc(*arc))
+    } else {
+        Err(CommandError::InvalidSelection("Only lines and arcs can be filleted or chamfered".to_string()))
+    }
+}
+
+fn segment_data(segment: FitSegment) -> EntityData {
+    match segment {
+        FitSegment::Line(line) => Box::new(line),
+        FitSegment::Arc(arc) => Box::new(arc),
+    }
+}
+
+/// Radius or chamfer distance from the prompt, else the named option, else 0
+fn corner_distance(explicit: Option<f64>, context: &CommandContext, option: &str) -> CommandResult<f64> {
+    match explicit {
+        Some(value) => Ok(value),
+        None => context.get_option(option).map_or(Ok(0.0), |value| parse_distance(value)),
+    }
+}
+
+fn parse_distance(input: &str) -> CommandResult<f64> {
+    match input.trim().parse::<f64>() {
+        Ok(value) if value >= 0.0 => Ok(value),
+        _ => Err(CommandError::InvalidInput(format!("Invalid distance: {}", input))),
+    }
+}
+
 #[derive(Clone)]
 pub struct BreakCommand {
     state: CommandState,
@@ -911,3 +1138,336 @@ impl Command for ExplodeCommand {
     fn clone_box(&self) -> Box<dyn Command> { Box::new(self.clone()) }
     fn as_any(&self) -> &dyn Any { self }
 }
+
+// ==================== CURVE FITTING COMMANDS ====================
+
+/// Tolerance used when neither the prompt nor the `tolerance` option sets one
+const DEFAULT_FIT_TOLERANCE: f64 = 0.01;
+
+type EntityData = Box<dyn Any + Send + Sync>;
+
+fn fit_tolerance(explicit: Option<f64>, context: &CommandContext) -> Result<f64, CommandError> {
+    let tolerance = explicit
+        .or_else(|| context.get_option("tolerance").and_then(|v| v.parse().ok()))
+        .unwrap_or(DEFAULT_FIT_TOLERANCE);
+    if tolerance > 0.0 {
+        Ok(tolerance)
+    } else {
+        Err(CommandError::InvalidInput("Tolerance must be positive".to_string()))
+    }
+}
+
+fn parse_tolerance(input: &str) -> Result<f64, CommandError> {
+    input
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| CommandError::InvalidInput(format!("Invalid tolerance: {}", input)))
+}
+
+/// Replace every selected entity `convert` accepts, returning the originals
+fn replace_entities<F>(
+    context: &mut CommandContext,
+    selection: &[EntityId],
+    convert: F,
+) -> Vec<(EntityId, EntityData)>
+where
+    F: Fn(&(dyn Any + Send + Sync)) -> Option<EntityData>,
+{
+    let mut originals = Vec::new();
+    for entity_id in selection {
+        let replacement = context
+            .document
+            .get_entity(entity_id)
+            .and_then(|entity| convert(&**entity));
+        if let Some(replacement) = replacement {
+            if let Some(original) = context.document.insert_entity(*entity_id, replacement) {
+                originals.push((*entity_id, original));
+            }
+        }
+    }
+    originals
+}
+
+fn restore_entities(context: &mut CommandContext, originals: &mut Vec<(EntityId, EntityData)>) -> CommandResult {
+    if originals.is_empty() {
+        return Err(CommandError::InvalidState("No entities to restore".to_string()));
+    }
+    for (entity_id, original) in originals.drain(..) {
+        context.document.insert_entity(entity_id, original);
+    }
+    Ok(())
+}
+
+/// FITARCS: replace polylines with lines and arcs
+pub struct FitArcsCommand {
+    selection: Vec<EntityId>,
+    tolerance: Option<f64>,
+    originals: Vec<(EntityId, EntityData)>,
+    state: CommandState,
+}
+
+impl FitArcsCommand {
+    pub fn new() -> Self {
+        Self {
+            selection: Vec::new(),
+            tolerance: None,
+            originals: Vec::new(),
+            state: CommandState::AwaitingParameter("tolerance".to_string()),
+        }
+    }
+}
+
+impl Default for FitArcsCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Command for FitArcsCommand {
+    fn name(&self) -> &str {
+        "FITARCS"
+    }
+
+    fn description(&self) -> &str {
+        "Replace polyline vertices with fitted lines and arcs"
+    }
+
+    fn usage(&self) -> &str {
+        "FITARCS <tolerance> (select polylines)"
+    }
+
+    fn execute(&mut self, context: &mut CommandContext) -> CommandResult {
+        if self.selection.is_empty() {
+            self.selection = context.selection.entities.clone();
+        }
+        if self.selection.is_empty() {
+            return Err(CommandError::InvalidSelection("No entities selected".to_string()));
+        }
+        let tolerance = fit_tolerance(self.tolerance, context)?;
+
+        self.originals = replace_entities(context, &self.selection, |entity| {
+            let polyline = entity.downcast_ref::<Polyline2D>()?;
+            let fitted = fitting::fit_arc_polyline(&polyline.vertices, tolerance, polyline.closed);
+            (fitted.vertices.len() < polyline.vertices.len()).then(|| Box::new(fitted) as EntityData)
+        });
+        if self.originals.is_empty() {
+            return Err(CommandError::InvalidSelection(
+                "No polylines could be fitted".to_string(),
+            ));
+        }
+
+        self.state = CommandState::Completed;
+        Ok(())
+    }
+
+    fn undo(&mut self, context: &mut CommandContext) -> CommandResult {
+        restore_entities(context, &mut self.originals)
+    }
+
+    fn state(&self) -> CommandState {
+        self.state.clone()
+    }
+
+    fn process_input(&mut self, input: &str, _context: &mut CommandContext) -> CommandResult {
+        self.tolerance = Some(parse_tolerance(input)?);
+        self.state = CommandState::Executing;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(FitArcsCommand {
+            selection: self.selection.clone(),
+            tolerance: self.tolerance,
+            originals: Vec::new(),
+            state: self.state.clone(),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// SIMPLIFY: refit splines with fewer control points
+pub struct SimplifySplineCommand {
+    selection: Vec<EntityId>,
+    tolerance: Option<f64>,
+    originals: Vec<(EntityId, EntityData)>,
+    state: CommandState,
+}
+
+impl SimplifySplineCommand {
+    pub fn new() -> Self {
+        Self {
+            selection: Vec::new(),
+            tolerance: None,
+            originals: Vec::new(),
+            state: CommandState::AwaitingParameter("tolerance".to_string()),
+        }
+    }
+}
+
+impl Default for SimplifySplineCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Command for SimplifySplineCommand {
+    fn name(&self) -> &str {
+        "SIMPLIFY"
+    }
+
+    fn description(&self) -> &str {
+        "Reduce spline control points within a tolerance"
+    }
+
+    fn usage(&self) -> &str {
+        "SIMPLIFY <tolerance> (select splines)"
+    }
+
+    fn execute(&mut self, context: &mut CommandContext) -> CommandResult {
+        if self.selection.is_empty() {
+            self.selection = context.selection.entities.clone();
+        }
+        if self.selection.is_empty() {
+            return Err(CommandError::InvalidSelection("No entities selected".to_string()));
+        }
+        let tolerance = fit_tolerance(self.tolerance, context)?;
+
+        self.originals = replace_entities(context, &self.selection, |entity| {
+            let reduced = if let Some(spline) = entity.downcast_ref::<BSpline>() {
+                fitting::reduce_control_points(spline, tolerance)
+            } else {
+                fitting::reduce_nurbs_control_points(entity.downcast_ref::<NurbsCurve>()?, tolerance)
+            };
+            reduced.map(|spline| Box::new(spline) as EntityData)
+        });
+        if self.originals.is_empty() {
+            return Err(CommandError::InvalidSelection(
+                "No splines could be simplified".to_string(),
+            ));
+        }
+
+        self.state = CommandState::Completed;
+        Ok(())
+    }
+
+    fn undo(&mut self, context: &mut CommandContext) -> CommandResult {
+        restore_entities(context, &mut self.originals)
+    }
+
+    fn state(&self) -> CommandState {
+        self.state.clone()
+    }
+
+    fn process_input(&mut self, input: &str, _context: &mut CommandContext) -> CommandResult {
+        self.tolerance = Some(parse_tolerance(input)?);
+        self.state = CommandState::Executing;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(SimplifySplineCommand {
+            selection: self.selection.clone(),
+            tolerance: self.tolerance,
+            originals: Vec::new(),
+            state: self.state.clone(),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// SPLINEARCS: convert splines to line/arc polylines for fabrication
+pub struct SplineToArcsCommand {
+    selection: Vec<EntityId>,
+    tolerance: Option<f64>,
+    originals: Vec<(EntityId, EntityData)>,
+    state: CommandState,
+}
+
+impl SplineToArcsCommand {
+    pub fn new() -> Self {
+        Self {
+            selection: Vec::new(),
+            tolerance: None,
+            originals: Vec::new(),
+            state: CommandState::AwaitingParameter("tolerance".to_string()),
+        }
+    }
+}
+
+impl Default for SplineToArcsCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Command for SplineToArcsCommand {
+    fn name(&self) -> &str {
+        "SPLINEARCS"
+    }
+
+    fn description(&self) -> &str {
+        "Convert splines to polylines of lines and arcs"
+    }
+
+    fn usage(&self) -> &str {
+        "SPLINEARCS <tolerance> (select splines)"
+    }
+
+    fn execute(&mut self, context: &mut CommandContext) -> CommandResult {
+        if self.selection.is_empty() {
+            self.selection = context.selection.entities.clone();
+        }
+        if self.selection.is_empty() {
+            return Err(CommandError::InvalidSelection("No entities selected".to_string()));
+        }
+        let tolerance = fit_tolerance(self.tolerance, context)?;
+
+        self.originals = replace_entities(context, &self.selection, |entity| {
+            let arcs = if let Some(spline) = entity.downcast_ref::<BSpline>() {
+                fitting::spline_to_arcs(spline, tolerance)
+            } else {
+                fitting::nurbs_to_arcs(entity.downcast_ref::<NurbsCurve>()?, tolerance)
+            };
+            Some(Box::new(arcs) as EntityData)
+        });
+        if self.originals.is_empty() {
+            return Err(CommandError::InvalidSelection("No splines selected".to_string()));
+        }
+
+        self.state = CommandState::Completed;
+        Ok(())
+    }
+
+    fn undo(&mut self, context: &mut CommandContext) -> CommandResult {
+        restore_entities(context, &mut self.originals)
+    }
+
+    fn state(&self) -> CommandState {
+        self.state.clone()
+    }
+
+    fn process_input(&mut self, input: &str, _context: &mut CommandContext) -> CommandResult {
+        self.tolerance = Some(parse_tolerance(input)?);
+        self.state = CommandState::Executing;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(SplineToArcsCommand {
+            selection: self.selection.clone(),
+            tolerance: self.tolerance,
+            originals: Vec::new(),
+            state: self.state.clone(),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
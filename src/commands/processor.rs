@@ -1,32 +1,61 @@
 // Command processor for CADDY CAD system
 // Handles command parsing, execution, queueing, and input processing
 
-use super::command::{Command, CommandContext, CommandError, CommandResult, CommandState, Point};
+use super::command::{
+    Command, CommandContext, CommandError, CommandResult, CommandState, Document, Point,
+};
 use super::history::UndoStack;
 use super::registry::CommandRegistry;
+use super::session::{SessionRecorder, SessionRecording, ViewportState};
+use crate::io::parameters::ParameterValues;
+use crate::io::units::Unit;
 use std::collections::VecDeque;
 
 /// Input parser for command arguments
+///
+/// Numbers may be typed as expressions (`25.4mm*3`, `sqrt(2)*10`,
+/// `width/2`), evaluated over the document parameters with lengths in
+/// drawing units; expressions with spaces or commas are quoted.
 pub struct InputParser {
     tokens: Vec<String>,
+    /// Whether each token follows its predecessor after a comma, as in `x,y,z`
+    joined: Vec<bool>,
     position: usize,
+    values: ParameterValues,
 }
 
 impl InputParser {
     /// Create a new input parser from a string
     pub fn new(input: &str) -> Self {
-        let tokens = Self::tokenize(input);
+        let (tokens, joined) = Self::tokenize(input);
         Self {
             tokens,
+            joined,
             position: 0,
+            values: ParameterValues::empty(Unit::Millimeters),
         }
     }
 
-    /// Tokenize input string
-    fn tokenize(input: &str) -> Vec<String> {
+    /// Evaluate numbers over `values`, in their drawing units
+    pub fn with_values(mut self, values: ParameterValues) -> Self {
+        self.values = values;
+        self
+    }
+
+    /// Value of a number or expression token
+    fn number(&self, token: &str, what: &str) -> CommandResult<f64> {
+        self.values
+            .evaluate(token)
+            .map_err(|_| CommandError::InvalidInput(format!("Invalid {}: {}", what, token)))
+    }
+
+    /// Tokenize input string, noting which tokens were joined by a comma
+    fn tokenize(input: &str) -> (Vec<String>, Vec<bool>) {
         let mut tokens = Vec::new();
+        let mut joined = Vec::new();
         let mut current_token = String::new();
         let mut in_quotes = false;
+        let mut after_comma = false;
 
         for ch in input.chars() {
             match ch {
@@ -36,7 +65,12 @@ impl InputParser {
                 ' ' | '\t' | ',' if !in_quotes => {
                     if !current_token.is_empty() {
                         tokens.push(current_token.clone());
+                        joined.push(after_comma);
                         current_token.clear();
+                        after_comma = false;
+                    }
+                    if ch == ',' {
+                        after_comma = !tokens.is_empty();
                     }
                 }
                 _ => {
@@ -47,9 +81,10 @@ impl InputParser {
 
         if !current_token.is_empty() {
             tokens.push(current_token);
+            joined.push(after_comma);
         }
 
-        tokens
+        (tokens, joined)
     }
 
     /// Get next token
@@ -77,32 +112,28 @@ impl InputParser {
         self.position < self.tokens.len()
     }
 
-    /// Parse a point coordinate (x,y or x,y,z)
+    /// Parse a point coordinate (x y, x,y or x,y,z)
+    ///
+    /// Z is only read as part of a comma-joined `x,y,z`, so a 2D point can be
+    /// followed by a numeric parameter.
     pub fn parse_point(&mut self) -> CommandResult<Point> {
         let x_str = self.next()
             .ok_or_else(|| CommandError::InvalidInput("Expected X coordinate".to_string()))?;
-        let x = x_str.parse::<f64>()
-            .map_err(|_| CommandError::InvalidInput(format!("Invalid X coordinate: {}", x_str)))?;
+        let x = self.number(&x_str, "X coordinate")?;
 
+        let y_joined = self.joined.get(self.position).copied().unwrap_or(false);
         let y_str = self.next()
             .ok_or_else(|| CommandError::InvalidInput("Expected Y coordinate".to_string()))?;
-        let y = y_str.parse::<f64>()
-            .map_err(|_| CommandError::InvalidInput(format!("Invalid Y coordinate: {}", y_str)))?;
-
-        // Z coordinate is optional
-        let z = if self.has_more() {
-            if let Some(z_str) = self.peek() {
-                if let Ok(z_val) = z_str.parse::<f64>() {
-                    self.next(); // Consume the token
-                    z_val
-                } else {
-                    0.0
-                }
-            } else {
-                0.0
+        let y = self.number(&y_str, "Y coordinate")?;
+
+        let z_joined = self.joined.get(self.position).copied().unwrap_or(false);
+        let z = match self.peek() {
+            Some(z_str) if y_joined && z_joined => {
+                let z = self.number(z_str, "Z coordinate")?;
+                self.position += 1;
+                z
             }
-        } else {
-            0.0
+            _ => 0.0,
         };
 
         Ok(Point::new(x, y, z))
@@ -112,24 +143,25 @@ impl InputParser {
     pub fn parse_distance(&mut self) -> CommandResult<f64> {
         let dist_str = self.next()
             .ok_or_else(|| CommandError::InvalidInput("Expected distance value".to_string()))?;
-        dist_str.parse::<f64>()
-            .map_err(|_| CommandError::InvalidInput(format!("Invalid distance: {}", dist_str)))
+        self.number(&dist_str, "distance")
     }
 
     /// Parse an angle value (in degrees)
     pub fn parse_angle(&mut self) -> CommandResult<f64> {
         let angle_str = self.next()
             .ok_or_else(|| CommandError::InvalidInput("Expected angle value".to_string()))?;
-        angle_str.parse::<f64>()
-            .map_err(|_| CommandError::InvalidInput(format!("Invalid angle: {}", angle_str)))
+        self.number(&angle_str, "angle")
     }
 
     /// Parse an integer value
     pub fn parse_integer(&mut self) -> CommandResult<i32> {
         let int_str = self.next()
             .ok_or_else(|| CommandError::InvalidInput("Expected integer value".to_string()))?;
-        int_str.parse::<i32>()
-            .map_err(|_| CommandError::InvalidInput(format!("Invalid integer: {}", int_str)))
+        let value = self.number(&int_str, "integer")?;
+        if value.fract() != 0.0 || value.abs() > i32::MAX as f64 {
+            return Err(CommandError::InvalidInput(format!("Invalid integer: {}", int_str)));
+        }
+        Ok(value as i32)
     }
 
     /// Parse a text string
@@ -184,6 +216,8 @@ pub struct CommandProcessor {
     last_command: Option<Box<dyn Command>>,
     /// Command chaining enabled
     chaining_enabled: bool,
+    /// Session recorder, when recording is on
+    recorder: Option<SessionRecorder>,
 }
 
 impl CommandProcessor {
@@ -196,6 +230,7 @@ impl CommandProcessor {
             current_command: None,
             last_command: None,
             chaining_enabled: false,
+            recorder: None,
         }
     }
 
@@ -208,11 +243,15 @@ impl CommandProcessor {
             current_command: None,
             last_command: None,
             chaining_enabled: false,
+            recorder: None,
         }
     }
 
     /// Execute a command by name with arguments
     pub fn execute(&mut self, command_line: &str, context: &mut CommandContext) -> CommandResult {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record_command(command_line, &context.selection);
+        }
         let mut parser = InputParser::new(command_line);
 
         // Get command name
@@ -259,7 +298,8 @@ impl CommandProcessor {
         let memento = command.create_memento(context);
 
         // Execute command
-        command.execute(context)?;
+        let name = command.name().to_string();
+        attributed(context, &name, |context| command.execute(context))?;
 
         // Add to history if the command can be undone
         if command.can_undo() {
@@ -278,8 +318,12 @@ impl CommandProcessor {
 
     /// Process input for multi-step command
     pub fn process_input(&mut self, input: &str, context: &mut CommandContext) -> CommandResult {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record_input(input);
+        }
         if let Some(ref mut command) = self.current_command {
-            command.process_input(input, context)?;
+            let name = command.name().to_string();
+            attributed(context, &name, |context| command.process_input(input, context))?;
 
             // Check if command is complete
             match command.state() {
@@ -307,14 +351,16 @@ impl CommandProcessor {
 
     /// Undo last command
     pub fn undo(&mut self, context: &mut CommandContext) -> CommandResult {
-        let description = self.history.undo(context)?;
+        let history = &mut self.history;
+        let description = attributed(context, "UNDO", |context| history.undo(context))?;
         println!("Undid: {}", description);
         Ok(())
     }
 
     /// Redo last undone command
     pub fn redo(&mut self, context: &mut CommandContext) -> CommandResult {
-        let description = self.history.redo(context)?;
+        let history = &mut self.history;
+        let description = attributed(context, "REDO", |context| history.redo(context))?;
         println!("Redid: {}", description);
         Ok(())
     }
@@ -328,7 +374,8 @@ impl CommandProcessor {
             let memento = command_clone.create_memento(context);
 
             // Execute command
-            command_clone.execute(context)?;
+            let name = command_clone.name().to_string();
+            attributed(context, &name, |context| command_clone.execute(context))?;
 
             // Add to history
             if command_clone.can_undo() {
@@ -344,6 +391,9 @@ impl CommandProcessor {
 
     /// Cancel current command
     pub fn cancel_current(&mut self) -> CommandResult {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record_cancel();
+        }
         if self.current_command.is_some() {
             self.current_command = None;
             Ok(())
@@ -367,7 +417,8 @@ impl CommandProcessor {
             let memento = command.create_memento(context);
 
             // Execute
-            if let Err(e) = command.execute(context) {
+            let name = command.name().to_string();
+            if let Err(e) = attributed(context, &name, |context| command.execute(context)) {
                 // End group and return error
                 self.history.end_group();
                 self.queue.clear(); // Clear remaining queue on error
@@ -447,6 +498,30 @@ impl CommandProcessor {
         self.history.end_group();
     }
 
+    /// Start recording commands, input and viewport changes
+    ///
+    /// Replaces any recording already in progress.
+    pub fn start_recording(&mut self, document: &Document) {
+        self.recorder = Some(SessionRecorder::start(document));
+    }
+
+    /// Stop recording and return what was captured
+    pub fn stop_recording(&mut self) -> Option<SessionRecording> {
+        self.recorder.take().map(SessionRecorder::finish)
+    }
+
+    /// Check if a session is being recorded
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Record the viewport, if recording
+    pub fn record_viewport(&mut self, state: ViewportState) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record_viewport(state);
+        }
+    }
+
     /// Get autocomplete suggestions
     pub fn autocomplete(&self, partial: &str) -> Vec<String> {
         self.registry.autocomplete(partial)
@@ -463,6 +538,18 @@ impl CommandProcessor {
     }
 }
 
+/// Run `f` with `name` recorded as the source of any document changes it makes
+fn attributed<T>(
+    context: &mut CommandContext,
+    name: &str,
+    f: impl FnOnce(&mut CommandContext) -> CommandResult<T>,
+) -> CommandResult<T> {
+    context.document.set_change_source(Some(name.to_string()));
+    let result = f(context);
+    context.document.set_change_source(None);
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -476,13 +563,31 @@ mod tests {
 
     #[test]
     fn test_input_parser_point() {
-        let mut parser = InputParser::new("10.5 20.5 30.5");
+        let mut parser = InputParser::new("10.5,20.5,30.5");
         let point = parser.parse_point().unwrap();
         assert_eq!(point.x, 10.5);
         assert_eq!(point.y, 20.5);
         assert_eq!(point.z, 30.5);
     }
 
+    #[test]
+    fn test_input_parser_2d_point_then_number() {
+        // A 2D point followed by a radius keeps the radius
+        let mut parser = InputParser::new("10,20 5");
+        let point = parser.parse_point().unwrap();
+        assert_eq!((point.x, point.y, point.z), (10.0, 20.0, 0.0));
+        assert_eq!(parser.parse_distance().unwrap(), 5.0);
+
+        let mut parser = InputParser::new("10 20 5");
+        assert_eq!(parser.parse_point().unwrap().z, 0.0);
+        assert_eq!(parser.parse_distance().unwrap(), 5.0);
+
+        let mut parser = InputParser::new("1, 2, 3 4");
+        assert_eq!(parser.parse_point().unwrap().z, 3.0);
+        assert_eq!(parser.parse_integer().unwrap(), 4);
+        assert!(InputParser::new("1,2,x").parse_point().is_err());
+    }
+
     #[test]
     fn test_input_parser_quoted_text() {
         let parser = InputParser::new(r#"TEXT "Hello World" 10 20"#);
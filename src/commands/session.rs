@@ -0,0 +1,421 @@
+// Session recording for CADDY CAD system
+// Captures command lines, prompt input and viewport changes into a compact
+// replay file, and plays them back against the document they were recorded on
+
+use super::command::{CommandContext, CommandError, Document, EntityId, SelectionSet};
+use super::processor::CommandProcessor;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+use thiserror::Error;
+
+/// Replay file magic bytes
+const MAGIC_BYTES: &[u8; 4] = b"CDYR";
+
+/// Replay file format version
+const FORMAT_VERSION: u16 = 1;
+
+/// Session recording errors
+#[derive(Debug, Error)]
+pub enum SessionError {
+    /// Reading or writing the replay file failed
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The file is not a replay file or is corrupt
+    #[error("Invalid replay file: {0}")]
+    Format(String),
+
+    /// The document differs from the one the session was recorded on
+    #[error("Recorded on document version {recorded}, current version is {actual}")]
+    VersionMismatch {
+        /// Document version at the start of recording
+        recorded: u64,
+        /// Version of the document replayed against
+        actual: u64,
+    },
+}
+
+/// Result type for session recording
+pub type SessionResult<T> = Result<T, SessionError>;
+
+/// What the viewport showed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ViewportState {
+    /// World point at the center of the view
+    pub center: (f64, f64),
+    /// Zoom factor
+    pub zoom: f64,
+    /// View twist in radians
+    pub rotation: f64,
+}
+
+/// One recorded user action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SessionEvent {
+    /// Command line passed to the processor, with the selection it ran on
+    Command {
+        /// Command name and arguments
+        line: String,
+        /// Selected entity IDs
+        selection: Vec<u64>,
+    },
+    /// Answer to a prompt of the active command
+    Input(String),
+    /// Active command cancelled
+    Cancel,
+    /// Viewport moved
+    Viewport(ViewportState),
+}
+
+/// Event with its time offset from the start of recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// Milliseconds since recording started
+    pub at_ms: u64,
+    /// The action
+    pub event: SessionEvent,
+}
+
+/// Where and on what a session was recorded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionHeader {
+    /// CADDY version that wrote the file
+    pub caddy_version: String,
+    /// Recording start time
+    pub started_at: DateTime<Utc>,
+    /// Document version when recording started
+    pub document_version: u64,
+    /// Entity count when recording started
+    pub entity_count: usize,
+}
+
+/// A finished recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecording {
+    /// Recording metadata
+    pub header: SessionHeader,
+    /// Actions in the order they happened
+    pub events: Vec<RecordedEvent>,
+}
+
+impl SessionRecording {
+    /// Length of the recording in milliseconds
+    pub fn duration_ms(&self) -> u64 {
+        self.events.last().map_or(0, |e| e.at_ms)
+    }
+
+    /// Encode as a replay file
+    pub fn to_bytes(&self) -> SessionResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC_BYTES);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        let body = bincode::serialize(self).map_err(|e| SessionError::Format(e.to_string()))?;
+        bytes.extend_from_slice(&body);
+        Ok(bytes)
+    }
+
+    /// Decode a replay file
+    pub fn from_bytes(bytes: &[u8]) -> SessionResult<Self> {
+        if bytes.len() < 6 || &bytes[..4] != MAGIC_BYTES {
+            return Err(SessionError::Format("missing replay header".to_string()));
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version > FORMAT_VERSION {
+            return Err(SessionError::Format(format!(
+                "unsupported format version {}",
+                version
+            )));
+        }
+        bincode::deserialize(&bytes[6..]).map_err(|e| SessionError::Format(e.to_string()))
+    }
+
+    /// Write the replay file
+    pub fn save(&self, path: impl AsRef<Path>) -> SessionResult<()> {
+        fs::write(path, self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Read a replay file
+    pub fn load(path: impl AsRef<Path>) -> SessionResult<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+}
+
+/// Records a session while it is running
+///
+/// Recording is opt-in: nothing is captured until [`SessionRecorder::start`].
+#[derive(Debug, Clone)]
+pub struct SessionRecorder {
+    header: SessionHeader,
+    started: Instant,
+    events: Vec<RecordedEvent>,
+    last_viewport: Option<ViewportState>,
+}
+
+impl SessionRecorder {
+    /// Start recording against the current state of `document`
+    pub fn start(document: &Document) -> Self {
+        Self {
+            header: SessionHeader {
+                caddy_version: env!("CARGO_PKG_VERSION").to_string(),
+                started_at: Utc::now(),
+                document_version: document.version(),
+                entity_count: document.entity_count(),
+            },
+            started: Instant::now(),
+            events: Vec::new(),
+            last_viewport: None,
+        }
+    }
+
+    /// Record a command line and the selection it runs on
+    pub fn record_command(&mut self, line: &str, selection: &SelectionSet) {
+        let selection = selection.entities.iter().map(|id| id.0).collect();
+        self.push(SessionEvent::Command {
+            line: line.to_string(),
+            selection,
+        });
+    }
+
+    /// Record prompt input
+    pub fn record_input(&mut self, input: &str) {
+        self.push(SessionEvent::Input(input.to_string()));
+    }
+
+    /// Record a cancel
+    pub fn record_cancel(&mut self) {
+        self.push(SessionEvent::Cancel);
+    }
+
+    /// Record the viewport; repeats of the last state are skipped
+    pub fn record_viewport(&mut self, state: ViewportState) {
+        if self.last_viewport != Some(state) {
+            self.last_viewport = Some(state);
+            self.push(SessionEvent::Viewport(state));
+        }
+    }
+
+    /// Number of events so far
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Whether nothing has been recorded yet
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Stop recording
+    pub fn finish(self) -> SessionRecording {
+        SessionRecording {
+            header: self.header,
+            events: self.events,
+        }
+    }
+
+    fn push(&mut self, event: SessionEvent) {
+        let at_ms = self.started.elapsed().as_millis() as u64;
+        self.events.push(RecordedEvent { at_ms, event });
+    }
+}
+
+/// Outcome of one replayed event
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayStep {
+    /// Index into the recording's events
+    pub index: usize,
+    /// Error the event produced on replay
+    pub error: Option<String>,
+}
+
+/// Result of a replay
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayReport {
+    /// Every replayed event in order
+    pub steps: Vec<ReplayStep>,
+    /// Document version after the replay
+    pub document_version: u64,
+}
+
+impl ReplayReport {
+    /// Events that failed on replay
+    pub fn failures(&self) -> impl Iterator<Item = &ReplayStep> {
+        self.steps.iter().filter(|s| s.error.is_some())
+    }
+}
+
+/// Re-executes a recording
+///
+/// Commands and input go through the processor exactly as typed, so a
+/// command that failed while recording fails again on replay. Viewport
+/// events are handed to a callback for the UI to apply.
+pub struct SessionPlayer {
+    recording: SessionRecording,
+    position: usize,
+}
+
+impl SessionPlayer {
+    /// Player positioned at the first event
+    pub fn new(recording: SessionRecording) -> Self {
+        Self {
+            recording,
+            position: 0,
+        }
+    }
+
+    /// The recording being played
+    pub fn recording(&self) -> &SessionRecording {
+        &self.recording
+    }
+
+    /// Index of the next event
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Whether every event has been played
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.recording.events.len()
+    }
+
+    /// Check that `document` is the one the session was recorded on
+    pub fn check(&self, document: &Document) -> SessionResult<()> {
+        let recorded = self.recording.header.document_version;
+        let actual = document.version();
+        if recorded != actual || self.recording.header.entity_count != document.entity_count() {
+            return Err(SessionError::VersionMismatch { recorded, actual });
+        }
+        Ok(())
+    }
+
+    /// Play the next event, returning the replayed event
+    pub fn step<F>(
+        &mut self,
+        processor: &mut CommandProcessor,
+        context: &mut CommandContext,
+        mut on_viewport: F,
+    ) -> Option<(&RecordedEvent, Result<(), CommandError>)>
+    where
+        F: FnMut(&ViewportState),
+    {
+        let recorded = self.recording.events.get(self.position)?;
+        self.position += 1;
+        let result = match &recorded.event {
+            SessionEvent::Command { line, selection } => {
+                context.selection = SelectionSet::from_entities(
+                    selection.iter().map(|&id| EntityId::new(id)).collect(),
+                );
+                processor.execute(line, context)
+            }
+            SessionEvent::Input(input) => processor.process_input(input, context),
+            SessionEvent::Cancel => {
+                // Nothing to cancel is not a divergence worth reporting
+                processor.cancel_current().ok();
+                Ok(())
+            }
+            SessionEvent::Viewport(state) => {
+                on_viewport(state);
+                Ok(())
+            }
+        };
+        Some((recorded, result))
+    }
+
+    /// Check the document, then play every remaining event
+    pub fn play<F>(
+        &mut self,
+        processor: &mut CommandProcessor,
+        context: &mut CommandContext,
+        mut on_viewport: F,
+    ) -> SessionResult<ReplayReport>
+    where
+        F: FnMut(&ViewportState),
+    {
+        if self.position == 0 {
+            self.check(&context.document)?;
+        }
+        let mut report = ReplayReport::default();
+        while !self.is_finished() {
+            let index = self.position;
+            if let Some((_, result)) = self.step(processor, context, &mut on_viewport) {
+                report.steps.push(ReplayStep {
+                    index,
+                    error: result.err().map(|e| e.to_string()),
+                });
+            }
+        }
+        report.document_version = context.document.version();
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{register_all_commands, CommandRegistry};
+
+    fn processor() -> CommandProcessor {
+        let mut registry = CommandRegistry::new();
+        register_all_commands(&mut registry);
+        CommandProcessor::new(registry)
+    }
+
+    fn view(zoom: f64) -> ViewportState {
+        ViewportState {
+            center: (50.0, 25.0),
+            zoom,
+            rotation: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_record_save_and_replay() {
+        let mut processor = processor();
+        let mut context = CommandContext::new(Document::new());
+        processor.start_recording(&context.document);
+
+        processor.record_viewport(view(1.0));
+        processor.record_viewport(view(1.0));
+        processor.execute("REGEN", &mut context).unwrap();
+        processor.process_input("0,0", &mut context).unwrap();
+        assert!(processor.execute("NOPE", &mut context).is_err());
+        assert!(processor.process_input("3,4", &mut context).is_err());
+        processor.record_viewport(view(2.0));
+
+        let recording = processor.stop_recording().unwrap();
+        assert!(!processor.is_recording());
+        assert_eq!(recording.events.len(), 6);
+        assert!(matches!(recording.events[0].event, SessionEvent::Viewport(_)));
+
+        let path = std::env::temp_dir().join(format!("session-{}.cdyr", uuid::Uuid::new_v4()));
+        recording.save(&path).unwrap();
+        let loaded = SessionRecording::load(&path).unwrap();
+        fs::remove_file(&path).ok();
+        assert_eq!(loaded, recording);
+        assert!(matches!(
+            SessionRecording::from_bytes(b"nope"),
+            Err(SessionError::Format(_))
+        ));
+
+        let mut replay = CommandContext::new(Document::new());
+        let mut views = Vec::new();
+        let report = SessionPlayer::new(loaded)
+            .play(&mut processor, &mut replay, |v| views.push(*v))
+            .unwrap();
+        assert_eq!(report.steps.len(), 6);
+        // Failures recur where they happened while recording
+        assert_eq!(report.failures().map(|s| s.index).collect::<Vec<_>>(), vec![3, 4]);
+        assert_eq!(views, vec![view(1.0), view(2.0)]);
+
+        let mut changed = Document::new();
+        changed.add_entity(Box::new(0u8));
+        let player = SessionPlayer::new(recording);
+        assert!(matches!(
+            player.check(&changed),
+            Err(SessionError::VersionMismatch { recorded: 0, actual: 1 })
+        ));
+    }
+}
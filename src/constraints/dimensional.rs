@@ -7,6 +7,8 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use super::geometric::EntityReference;
+use crate::io::parameters::ParameterValues;
+use crate::takeoff::formula::Formula;
 
 /// Dimensional constraint type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -216,10 +218,13 @@ impl DimensionalConstraint {
     }
 
     /// Get effective value (evaluates expression if present)
-    pub fn get_effective_value(&self, _parameters: &ParameterTable) -> f64 {
-        // In real implementation, would evaluate expression
-        // For now, just return the stored value
-        self.value
+    ///
+    /// Falls back to the stored value when the expression can't be evaluated.
+    pub fn get_effective_value(&self, parameters: &ParameterTable) -> f64 {
+        self.expression
+            .as_deref()
+            .and_then(|expression| parameters.evaluate(expression).ok())
+            .unwrap_or(self.value)
     }
 
     /// Check if current geometry satisfies constraint
@@ -316,54 +321,22 @@ impl ParameterTable {
         self.parameters.keys().cloned().collect()
     }
 
-    /// Evaluate a simple expression (simplified parser)
+    /// Evaluate an expression over the parameters
+    ///
+    /// Uses the takeoff formula syntax: `+ - * / ^`, parentheses and
+    /// functions such as `min`, `max` and `round`.
     pub fn evaluate(&self, expression: &str) -> Result<f64, String> {
-        // Simple implementation - real one would use a proper expression parser
-        // For now, just handle parameter lookups and simple arithmetic
-
-        let expr = expression.trim();
-
-        // Try to parse as number
-        if let Ok(value) = expr.parse::<f64>() {
-            return Ok(value);
-        }
-
-        // Try to lookup as parameter
-        if let Some(value) = self.get(expr) {
-            return Ok(value);
-        }
-
-        // Simple arithmetic operators
-        if let Some(pos) = expr.find('+') {
-            let left = self.evaluate(&expr[..pos])?;
-            let right = self.evaluate(&expr[pos + 1..])?;
-            return Ok(left + right);
-        }
-
-        if let Some(pos) = expr.rfind('-') {
-            if pos > 0 {
-                let left = self.evaluate(&expr[..pos])?;
-                let right = self.evaluate(&expr[pos + 1..])?;
-                return Ok(left - right);
-            }
-        }
-
-        if let Some(pos) = expr.find('*') {
-            let left = self.evaluate(&expr[..pos])?;
-            let right = self.evaluate(&expr[pos + 1..])?;
-            return Ok(left * right);
-        }
+        let formula = Formula::parse(expression).map_err(|e| e.to_string())?;
+        formula.evaluate(&self.parameters).map_err(|e| e.to_string())
+    }
+}
 
-        if let Some(pos) = expr.find('/') {
-            let left = self.evaluate(&expr[..pos])?;
-            let right = self.evaluate(&expr[pos + 1..])?;
-            if right.abs() < 1e-10 {
-                return Err("Division by zero".to_string());
-            }
-            return Ok(left / right);
+impl From<&ParameterValues> for ParameterTable {
+    /// Document parameter values in drawing units, for the constraint solver
+    fn from(values: &ParameterValues) -> Self {
+        ParameterTable {
+            parameters: values.inputs(),
         }
-
-        Err(format!("Cannot evaluate expression: {}", expr))
     }
 }
 
@@ -456,6 +429,11 @@ mod tests {
             .with_expression("width * 2");
 
         assert_eq!(constraint.expression, Some("width * 2".to_string()));
+
+        let mut params = ParameterTable::new();
+        assert_eq!(constraint.get_effective_value(&params), 50.0);
+        params.set("width", 30.0);
+        assert_eq!(constraint.get_effective_value(&params), 60.0);
     }
 
     #[test]
@@ -496,6 +474,12 @@ mod tests {
         assert_eq!(params.get("width"), Some(100.0));
         assert_eq!(params.get("height"), Some(50.0));
         assert_eq!(params.get("depth"), None);
+
+        let mut document = crate::io::parameters::Parameters::new();
+        document.set("width", "1.2", Some(crate::io::units::Unit::Meters)).unwrap();
+        let values = document.evaluate(crate::io::units::Unit::Millimeters).unwrap();
+        let table = ParameterTable::from(&values);
+        assert!((table.get("width").unwrap() - 1200.0).abs() < 1e-9);
     }
 
     #[test]
@@ -509,6 +493,8 @@ mod tests {
         assert_eq!(params.evaluate("d1 - d2").unwrap(), 5.0);
         assert_eq!(params.evaluate("d1 * d2").unwrap(), 50.0);
         assert_eq!(params.evaluate("d1 / d2").unwrap(), 2.0);
+        assert_eq!(params.evaluate("d1 - d2 - 1").unwrap(), 4.0);
+        assert_eq!(params.evaluate("(d1 + d2) * 2").unwrap(), 30.0);
     }
 
     #[test]
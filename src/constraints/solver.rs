@@ -8,7 +8,7 @@ use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 use super::geometric::{GeometricConstraint, GeometricConstraintType};
-use super::dimensional::{DimensionalConstraint, ConstraintEquation, ConstraintMode};
+use super::dimensional::{DimensionalConstraint, ConstraintEquation, ConstraintMode, ParameterTable};
 
 /// Solver status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -171,6 +171,25 @@ impl ConstraintSolver {
         self.status = SolverStatus::NotSolved;
     }
 
+    /// Set driving constraint values from their expressions
+    ///
+    /// Returns the constraints whose expressions could not be evaluated;
+    /// those keep their current value.
+    pub fn apply_parameters(&mut self, parameters: &ParameterTable) -> Vec<Uuid> {
+        let mut failed = Vec::new();
+        for constraint in &mut self.dimensional_constraints {
+            let Some(expression) = constraint.expression.as_deref() else {
+                continue;
+            };
+            match parameters.evaluate(expression) {
+                Ok(value) => constraint.set_value(value),
+                Err(_) => failed.push(constraint.id),
+            }
+        }
+        self.status = SolverStatus::NotSolved;
+        failed
+    }
+
     /// Get solver status
     pub fn status(&self) -> SolverStatus {
         self.status
@@ -470,6 +489,7 @@ pub struct SolverDiagnostics {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constraints::geometric::EntityReference;
 
     #[test]
     fn test_solver_creation() {
@@ -496,6 +516,28 @@ mod tests {
         assert_eq!(diag.dimensional_constraint_count, 1);
     }
 
+    #[test]
+    fn test_apply_parameters() {
+        let mut solver = ConstraintSolver::new();
+
+        let p1 = EntityReference::Point(Uuid::new_v4());
+        let p2 = EntityReference::Point(Uuid::new_v4());
+        let circle = EntityReference::Circle(Uuid::new_v4());
+
+        solver.add_dimensional_constraint(
+            DimensionalConstraint::distance(p1, p2, 50.0).with_expression("width / 2"),
+        );
+        let unbound = DimensionalConstraint::radius(circle, 5.0).with_expression("depth");
+        let unbound_id = unbound.id;
+        solver.add_dimensional_constraint(unbound);
+
+        let mut params = ParameterTable::new();
+        params.set("width", 120.0);
+        assert_eq!(solver.apply_parameters(&params), vec![unbound_id]);
+        assert_eq!(solver.dimensional_constraints[0].value, 60.0);
+        assert_eq!(solver.dimensional_constraints[1].value, 5.0);
+    }
+
     #[test]
     fn test_dof_analysis() {
         let mut solver = ConstraintSolver::new();
@@ -171,6 +171,18 @@ impl Transform2D {
         }
     }
 
+    /// Create shear transform moving x by `k` times y
+    #[inline]
+    pub fn shear(k: f64) -> Self {
+        Self {
+            matrix: Matrix3::new(
+                1.0, k, 0.0,
+                0.0, 1.0, 0.0,
+                0.0, 0.0, 1.0,
+            ),
+        }
+    }
+
     /// Transform a point (applies translation)
     #[inline]
     pub fn transform_point(&self, point: &Vector2) -> Vector2 {
@@ -198,6 +210,112 @@ impl Transform2D {
             matrix: other.matrix * self.matrix,
         }
     }
+
+    /// Extract the translation component
+    #[inline]
+    pub fn translation_component(&self) -> Vector2 {
+        Vector2::new(self.matrix[(0, 2)], self.matrix[(1, 2)])
+    }
+
+    /// Determinant of the linear part; the factor by which areas scale
+    #[inline]
+    pub fn determinant(&self) -> f64 {
+        self.matrix.fixed_view::<2, 2>(0, 0).determinant()
+    }
+
+    /// Whether the transform is affine (no projective bottom row)
+    #[inline]
+    pub fn is_affine(&self) -> bool {
+        self.matrix[(2, 0)].approx_zero()
+            && self.matrix[(2, 1)].approx_zero()
+            && self.matrix[(2, 2)].approx_eq(&1.0)
+    }
+
+    /// Whether the transform mirrors, reversing the winding of shapes
+    #[inline]
+    pub fn is_mirrored(&self) -> bool {
+        self.determinant() < 0.0
+    }
+
+    /// Split into translation, rotation, scale and shear
+    ///
+    /// Returns `None` for singular or projective transforms.
+    pub fn decompose(&self) -> Option<Decomposition2D> {
+        if !self.is_affine() {
+            return None;
+        }
+        let linear = self.matrix.fixed_view::<2, 2>(0, 0).into_owned();
+        let column = linear.column(0).into_owned();
+        let length = column.norm();
+        if length < EPSILON || self.determinant().abs() < EPSILON * EPSILON {
+            return None;
+        }
+
+        // Mirroring goes into a negative x scale, so the rotation stays proper
+        let axis = if self.is_mirrored() { -column / length } else { column / length };
+        let rotation = axis.y.atan2(axis.x);
+        let (sin, cos) = rotation.sin_cos();
+        let unrotated = nalgebra::Matrix2::new(cos, sin, -sin, cos) * linear;
+        let scale = Vector2::new(unrotated[(0, 0)], unrotated[(1, 1)]);
+
+        Some(Decomposition2D {
+            translation: self.translation_component(),
+            rotation,
+            scale,
+            shear: unrotated[(0, 1)] / scale.y,
+        })
+    }
+
+    /// Interpolate between two affine transforms by their decompositions
+    ///
+    /// Translation, scale and shear are interpolated linearly and rotation
+    /// the short way round, so rigid motions stay rigid throughout. Returns
+    /// `None` if either transform cannot be decomposed.
+    pub fn interpolate(&self, other: &Transform2D, t: f64) -> Option<Self> {
+        let (a, b) = (self.decompose()?, other.decompose()?);
+        let turn = (b.rotation - a.rotation + std::f64::consts::PI).rem_euclid(std::f64::consts::TAU)
+            - std::f64::consts::PI;
+        Some(
+            Decomposition2D {
+                translation: lerp_vec2(&a.translation, &b.translation, t),
+                rotation: a.rotation + turn * t,
+                scale: lerp_vec2(&a.scale, &b.scale, t),
+                shear: a.shear + (b.shear - a.shear) * t,
+            }
+            .to_transform(),
+        )
+    }
+}
+
+/// Affine 2D transform split into its parts
+///
+/// The transform is recomposed as scale, then shear, then rotation, then
+/// translation. A mirrored transform has a negative x scale.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Decomposition2D {
+    /// Translation
+    pub translation: Vector2,
+    /// Rotation angle in radians, in (-π, π]
+    pub rotation: f64,
+    /// Scale along x and y
+    pub scale: Vector2,
+    /// Shear moving x by this factor times y
+    pub shear: f64,
+}
+
+impl Decomposition2D {
+    /// Rebuild the transform
+    pub fn to_transform(&self) -> Transform2D {
+        Transform2D::scale_non_uniform(self.scale.x, self.scale.y)
+            .then(&Transform2D::shear(self.shear))
+            .then(&Transform2D::rotation(self.rotation))
+            .then(&Transform2D::translation(self.translation.x, self.translation.y))
+    }
+
+    /// Whether the scale differs between axes or there is shear
+    pub fn is_non_uniform(&self) -> bool {
+        !self.scale.x.abs().approx_eq(&self.scale.y.abs()) || !self.shear.approx_zero()
+    }
 }
 
 impl Default for Transform2D {
@@ -346,6 +464,20 @@ impl Transform3D {
         }
     }
 
+    /// Create shear transform moving x by `xy` times y and `xz` times z, and
+    /// y by `yz` times z
+    #[inline]
+    pub fn shear(xy: f64, xz: f64, yz: f64) -> Self {
+        Self {
+            matrix: Matrix4::new(
+                1.0, xy, xz, 0.0,
+                0.0, 1.0, yz, 0.0,
+                0.0, 0.0, 1.0, 0.0,
+                0.0, 0.0, 0.0, 1.0,
+            ),
+        }
+    }
+
     /// Transform a point (applies translation)
     #[inline]
     pub fn transform_point(&self, point: &Vector3) -> Vector3 {
@@ -384,6 +516,84 @@ impl Transform3D {
         )
     }
 
+    /// Determinant of the linear part; the factor by which volumes scale
+    #[inline]
+    pub fn determinant(&self) -> f64 {
+        self.matrix.fixed_view::<3, 3>(0, 0).determinant()
+    }
+
+    /// Whether the transform is affine (no projective bottom row)
+    #[inline]
+    pub fn is_affine(&self) -> bool {
+        (0..3).all(|j| self.matrix[(3, j)].approx_zero()) && self.matrix[(3, 3)].approx_eq(&1.0)
+    }
+
+    /// Whether the transform mirrors, turning right-handed frames left-handed
+    #[inline]
+    pub fn is_mirrored(&self) -> bool {
+        self.determinant() < 0.0
+    }
+
+    /// Split into translation, rotation, scale and shear
+    ///
+    /// Returns `None` for singular or projective transforms.
+    pub fn decompose(&self) -> Option<Decomposition3D> {
+        if !self.is_affine() || self.determinant().abs() < EPSILON * EPSILON * EPSILON {
+            return None;
+        }
+        let linear = self.matrix.fixed_view::<3, 3>(0, 0).into_owned();
+
+        // Gram-Schmidt on the columns gives the rotation; mirroring goes into
+        // a negative x scale so the rotation stays proper
+        let mut axes = [Vector3::zeros(); 3];
+        for i in 0..3 {
+            let mut axis = linear.column(i).into_owned();
+            for previous in &axes[..i] {
+                axis -= previous * previous.dot(&axis);
+            }
+            let length = axis.norm();
+            if length < EPSILON {
+                return None;
+            }
+            axes[i] = axis / length;
+        }
+        if self.is_mirrored() {
+            axes[0] = -axes[0];
+        }
+        let rotation = Matrix3::from_columns(&axes);
+        let unrotated = rotation.transpose() * linear;
+        let scale = Vector3::new(unrotated[(0, 0)], unrotated[(1, 1)], unrotated[(2, 2)]);
+
+        Some(Decomposition3D {
+            translation: self.translation_component(),
+            rotation: UnitQuaternion::from_matrix(&rotation),
+            scale,
+            shear: Vector3::new(
+                unrotated[(0, 1)] / scale.y,
+                unrotated[(0, 2)] / scale.z,
+                unrotated[(1, 2)] / scale.z,
+            ),
+        })
+    }
+
+    /// Interpolate between two affine transforms by their decompositions
+    ///
+    /// Translation, scale and shear are interpolated linearly and rotation
+    /// by spherical interpolation, so rigid motions stay rigid throughout.
+    /// Returns `None` if either transform cannot be decomposed.
+    pub fn interpolate(&self, other: &Transform3D, t: f64) -> Option<Self> {
+        let (a, b) = (self.decompose()?, other.decompose()?);
+        Some(
+            Decomposition3D {
+                translation: lerp_vec3(&a.translation, &b.translation, t),
+                rotation: slerp_quat(&a.rotation, &b.rotation, t),
+                scale: lerp_vec3(&a.scale, &b.scale, t),
+                shear: lerp_vec3(&a.shear, &b.shear, t),
+            }
+            .to_transform(),
+        )
+    }
+
     /// Create a look-at view matrix
     #[inline]
     pub fn look_at(eye: &Vector3, target: &Vector3, up: &Vector3) -> Self {
@@ -413,6 +623,38 @@ impl Transform3D {
     }
 }
 
+/// Affine 3D transform split into its parts
+///
+/// The transform is recomposed as scale, then shear, then rotation, then
+/// translation. A mirrored transform has a negative x scale.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Decomposition3D {
+    /// Translation
+    pub translation: Vector3,
+    /// Rotation
+    pub rotation: UnitQuaternion<f64>,
+    /// Scale along x, y and z
+    pub scale: Vector3,
+    /// Shear factors as taken by [`Transform3D::shear`]: xy, xz and yz
+    pub shear: Vector3,
+}
+
+impl Decomposition3D {
+    /// Rebuild the transform
+    pub fn to_transform(&self) -> Transform3D {
+        Transform3D::scale_non_uniform(self.scale.x, self.scale.y, self.scale.z)
+            .then(&Transform3D::shear(self.shear.x, self.shear.y, self.shear.z))
+            .then(&Transform3D::from_quaternion(&self.rotation))
+            .then(&Transform3D::translation_vec(&self.translation))
+    }
+
+    /// Whether the scale differs between axes or there is shear
+    pub fn is_non_uniform(&self) -> bool {
+        let s = self.scale.abs();
+        !s.x.approx_eq(&s.y) || !s.x.approx_eq(&s.z) || !self.shear.approx_zero()
+    }
+}
+
 impl Default for Transform3D {
     fn default() -> Self {
         Self::identity()
@@ -582,6 +824,65 @@ mod tests {
         assert!(transformed.approx_eq_eps(&Vector3::new(0.0, 1.0, 0.0), 1e-10));
     }
 
+    #[test]
+    fn test_transform2d_decompose() {
+        // A rotated block with non-uniform scale, nested in a rotated block:
+        // the combined transform has shear
+        let inner = Transform2D::scale_non_uniform(2.0, 0.5).then(&Transform2D::rotation(0.3));
+        let outer = Transform2D::scale_non_uniform(1.0, 3.0)
+            .then(&Transform2D::rotation(-1.1))
+            .then(&Transform2D::translation(5.0, -2.0));
+        let combined = inner.then(&outer);
+        let parts = combined.decompose().unwrap();
+        assert!(!parts.shear.approx_zero());
+        assert!(parts.is_non_uniform());
+        assert!(parts.to_transform().approx_eq_eps(&combined, 1e-10));
+
+        let mirrored = Transform2D::scale_non_uniform(-2.0, 3.0).then(&Transform2D::rotation(0.7));
+        assert!(mirrored.is_mirrored());
+        let parts = mirrored.decompose().unwrap();
+        assert!(parts.scale.approx_eq_eps(&Vector2::new(-2.0, 3.0), 1e-10));
+        assert!(parts.rotation.approx_eq_eps(&0.7, 1e-10));
+        assert!(parts.shear.approx_zero_eps(1e-10));
+
+        assert!(Transform2D::scale_non_uniform(1.0, 0.0).decompose().is_none());
+    }
+
+    #[test]
+    fn test_transform2d_interpolate() {
+        let a = Transform2D::rotation(PI * 0.9).then(&Transform2D::translation(0.0, 0.0));
+        let b = Transform2D::rotation(-PI * 0.9).then(&Transform2D::translation(10.0, 0.0));
+        let mid = a.interpolate(&b, 0.5).unwrap();
+        // The short way round passes through a half turn, staying rigid
+        assert!(mid.approx_eq_eps(&Transform2D::rotation(PI).then(&Transform2D::translation(5.0, 0.0)), 1e-10));
+        assert!(a.interpolate(&b, 0.0).unwrap().approx_eq_eps(&a, 1e-10));
+        assert!(a.interpolate(&b, 1.0).unwrap().approx_eq_eps(&b, 1e-10));
+    }
+
+    #[test]
+    fn test_transform3d_decompose() {
+        let combined = Transform3D::scale_non_uniform(2.0, 0.5, 1.5)
+            .then(&Transform3D::rotation(&Vector3::new(1.0, 2.0, 3.0), 0.8))
+            .then(&Transform3D::scale_non_uniform(1.0, 3.0, 0.25))
+            .then(&Transform3D::rotation_x(-0.4))
+            .then(&Transform3D::translation(1.0, 2.0, 3.0));
+        let parts = combined.decompose().unwrap();
+        assert!(!parts.shear.approx_zero());
+        assert!(parts.to_transform().approx_eq_eps(&combined, 1e-10));
+
+        let mirrored = Transform3D::scale_non_uniform(1.0, 1.0, -1.0).then(&Transform3D::rotation_z(0.5));
+        assert!(mirrored.is_mirrored());
+        let parts = mirrored.decompose().unwrap();
+        assert!(parts.scale.x < 0.0);
+        assert!(parts.to_transform().approx_eq_eps(&mirrored, 1e-10));
+
+        let a = Transform3D::rotation_z(0.2);
+        let b = Transform3D::rotation_z(1.0).then(&Transform3D::translation(4.0, 0.0, 0.0));
+        let mid = a.interpolate(&b, 0.5).unwrap();
+        assert!(mid.approx_eq_eps(&Transform3D::rotation_z(0.6).then(&Transform3D::translation(2.0, 0.0, 0.0)), 1e-10));
+        assert!(Transform3D::perspective(1.0, 1.5, 0.1, 100.0).decompose().is_none());
+    }
+
     #[test]
     fn test_cross_2d() {
         let a = Vector2::new(1.0, 0.0);
@@ -11,8 +11,13 @@ pub mod primitives;
 
 // Re-export commonly used types
 pub use color::Color;
-pub use math::{Matrix3, Matrix4, Quaternion, Transform2D, Transform3D, Vector2, Vector3, Vector4};
-pub use precision::{ApproxEq, EPSILON, EPSILON_FINE, EPSILON_NORMAL, EPSILON_ROUGH};
+pub use math::{
+    Decomposition2D, Decomposition3D, Matrix3, Matrix4, Quaternion, Transform2D, Transform3D,
+    Vector2, Vector3, Vector4,
+};
+pub use precision::{
+    ApproxEq, PredicateMode, EPSILON, EPSILON_FINE, EPSILON_NORMAL, EPSILON_ROUGH,
+};
 pub use primitives::{
     BoundingBox2, BoundingBox3, EntityId, Plane, Point2, Point3, Ray2, Ray3,
 };
@@ -3,6 +3,14 @@
 //! This module provides epsilon constants and traits for floating-point
 //! comparison with configurable tolerance levels, essential for robust
 //! CAD geometry operations.
+//!
+//! It also provides adaptive-precision orientation and incircle predicates
+//! in the style of Shewchuk: a floating-point evaluation with a forward error
+//! bound answers almost every query, and only when the result is too close
+//! to zero to trust is the determinant recomputed exactly with expansion
+//! arithmetic. Whether geometry code relies on these exact signs or on
+//! [`EPSILON`] comparisons is selected per operation by the
+//! [`PredicateMode`] of the [`Tolerance`] it is given.
 
 use serde::{Deserialize, Serialize};
 use std::f64::consts::PI;
@@ -155,12 +163,19 @@ pub struct Tolerance {
     pub distance: f64,
     /// Angular tolerance in radians
     pub angle: f64,
+    /// How predicates decide signs
+    #[serde(default)]
+    pub predicates: PredicateMode,
 }
 
 impl Tolerance {
     /// Create a new tolerance with custom values
     pub fn new(distance: f64, angle: f64) -> Self {
-        Self { distance, angle }
+        Self {
+            distance,
+            angle,
+            predicates: PredicateMode::Tolerance,
+        }
     }
 
     /// Rough tolerance preset
@@ -168,6 +183,7 @@ impl Tolerance {
         Self {
             distance: EPSILON_ROUGH,
             angle: 1e-4, // ~0.0057 degrees
+            predicates: PredicateMode::Tolerance,
         }
     }
 
@@ -176,6 +192,7 @@ impl Tolerance {
         Self {
             distance: EPSILON_NORMAL,
             angle: 1e-7, // ~0.0000057 degrees
+            predicates: PredicateMode::Tolerance,
         }
     }
 
@@ -184,8 +201,15 @@ impl Tolerance {
         Self {
             distance: EPSILON_FINE,
             angle: 1e-10,
+            predicates: PredicateMode::Tolerance,
         }
     }
+
+    /// The same tolerance with predicates deciding signs by `mode`
+    pub fn with_predicates(mut self, mode: PredicateMode) -> Self {
+        self.predicates = mode;
+        self
+    }
 }
 
 impl Default for Tolerance {
@@ -194,6 +218,268 @@ impl Default for Tolerance {
     }
 }
 
+/// How sign decisions in geometric predicates are made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PredicateMode {
+    /// Values within a tolerance of zero count as degenerate (default)
+    #[default]
+    Tolerance,
+    /// Signs are computed exactly, so only truly degenerate input (exactly
+    /// collinear or coplanar points) counts as degenerate
+    Exact,
+}
+
+/// Sign of [`orient2d`] under the tolerance's [`PredicateMode`]: in
+/// tolerance mode, values within its distance of zero give 0
+pub fn orient2d_sign(a: [f64; 2], b: [f64; 2], c: [f64; 2], tolerance: &Tolerance) -> i8 {
+    match tolerance.predicates {
+        PredicateMode::Exact => sign(orient2d(a, b, c)),
+        PredicateMode::Tolerance => {
+            let det = (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+            if det.abs() <= tolerance.distance {
+                0
+            } else {
+                sign(det)
+            }
+        }
+    }
+}
+
+/// Twice the signed area of triangle `a b c`, with an exact sign: positive
+/// when the points turn counter-clockwise, zero when they are collinear
+pub fn orient2d(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+    let left = (a[0] - c[0]) * (b[1] - c[1]);
+    let right = (a[1] - c[1]) * (b[0] - c[0]);
+    let det = left - right;
+
+    let sum = if left > 0.0 {
+        if right <= 0.0 {
+            return det;
+        }
+        left + right
+    } else if left < 0.0 {
+        if right >= 0.0 {
+            return det;
+        }
+        -left - right
+    } else {
+        return det;
+    };
+
+    if det.abs() >= CCW_ERROR_BOUND * sum {
+        return det;
+    }
+
+    let [acx, acy, bcx, bcy] = [
+        two_diff(a[0], c[0]),
+        two_diff(a[1], c[1]),
+        two_diff(b[0], c[0]),
+        two_diff(b[1], c[1]),
+    ];
+    most_significant(&expansion_diff(
+        &expansion_product(&acx, &bcy),
+        &expansion_product(&acy, &bcx),
+    ))
+}
+
+/// Six times the signed volume of tetrahedron `a b c d`, with an exact
+/// sign: positive when `d` lies below the plane through `a`, `b`, `c`, where
+/// "above" is the side from which they appear counter-clockwise
+pub fn orient3d(a: [f64; 3], b: [f64; 3], c: [f64; 3], d: [f64; 3]) -> f64 {
+    let [adx, ady, adz] = [a[0] - d[0], a[1] - d[1], a[2] - d[2]];
+    let [bdx, bdy, bdz] = [b[0] - d[0], b[1] - d[1], b[2] - d[2]];
+    let [cdx, cdy, cdz] = [c[0] - d[0], c[1] - d[1], c[2] - d[2]];
+
+    let (bdxcdy, cdxbdy) = (bdx * cdy, cdx * bdy);
+    let (cdxady, adxcdy) = (cdx * ady, adx * cdy);
+    let (adxbdy, bdxady) = (adx * bdy, bdx * ady);
+
+    let det = adz * (bdxcdy - cdxbdy) + bdz * (cdxady - adxcdy) + cdz * (adxbdy - bdxady);
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * adz.abs()
+        + (cdxady.abs() + adxcdy.abs()) * bdz.abs()
+        + (adxbdy.abs() + bdxady.abs()) * cdz.abs();
+    if det.abs() > ORIENT3D_ERROR_BOUND * permanent {
+        return det;
+    }
+
+    let ad = [two_diff(a[0], d[0]), two_diff(a[1], d[1]), two_diff(a[2], d[2])];
+    let bd = [two_diff(b[0], d[0]), two_diff(b[1], d[1]), two_diff(b[2], d[2])];
+    let cd = [two_diff(c[0], d[0]), two_diff(c[1], d[1]), two_diff(c[2], d[2])];
+
+    // Each 2x2 minor of the x and y columns, weighted by the z column
+    let minor = |p: &[Vec<f64>; 3], q: &[Vec<f64>; 3]| {
+        expansion_diff(&expansion_product(&p[0], &q[1]), &expansion_product(&q[0], &p[1]))
+    };
+    let terms = [
+        expansion_product(&ad[2], &minor(&bd, &cd)),
+        expansion_product(&bd[2], &minor(&cd, &ad)),
+        expansion_product(&cd[2], &minor(&ad, &bd)),
+    ];
+    most_significant(&expansion_sum(&expansion_sum(&terms[0], &terms[1]), &terms[2]))
+}
+
+/// Positive when `d` lies inside the circle through counter-clockwise
+/// `a`, `b`, `c`, negative outside and zero on it, with an exact sign
+pub fn incircle(a: [f64; 2], b: [f64; 2], c: [f64; 2], d: [f64; 2]) -> f64 {
+    let [adx, ady] = [a[0] - d[0], a[1] - d[1]];
+    let [bdx, bdy] = [b[0] - d[0], b[1] - d[1]];
+    let [cdx, cdy] = [c[0] - d[0], c[1] - d[1]];
+
+    let (bdxcdy, cdxbdy) = (bdx * cdy, cdx * bdy);
+    let (cdxady, adxcdy) = (cdx * ady, adx * cdy);
+    let (adxbdy, bdxady) = (adx * bdy, bdx * ady);
+    let alift = adx * adx + ady * ady;
+    let blift = bdx * bdx + bdy * bdy;
+    let clift = cdx * cdx + cdy * cdy;
+
+    let det = alift * (bdxcdy - cdxbdy) + blift * (cdxady - adxcdy) + clift * (adxbdy - bdxady);
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * alift
+        + (cdxady.abs() + adxcdy.abs()) * blift
+        + (adxbdy.abs() + bdxady.abs()) * clift;
+    if det.abs() > INCIRCLE_ERROR_BOUND * permanent {
+        return det;
+    }
+
+    let ad = [two_diff(a[0], d[0]), two_diff(a[1], d[1])];
+    let bd = [two_diff(b[0], d[0]), two_diff(b[1], d[1])];
+    let cd = [two_diff(c[0], d[0]), two_diff(c[1], d[1])];
+
+    let lift = |p: &[Vec<f64>; 2]| {
+        expansion_sum(&expansion_product(&p[0], &p[0]), &expansion_product(&p[1], &p[1]))
+    };
+    let minor = |p: &[Vec<f64>; 2], q: &[Vec<f64>; 2]| {
+        expansion_diff(&expansion_product(&p[0], &q[1]), &expansion_product(&q[0], &p[1]))
+    };
+    let terms = [
+        expansion_product(&lift(&ad), &minor(&bd, &cd)),
+        expansion_product(&lift(&bd), &minor(&cd, &ad)),
+        expansion_product(&lift(&cd), &minor(&ad, &bd)),
+    ];
+    most_significant(&expansion_sum(&expansion_sum(&terms[0], &terms[1]), &terms[2]))
+}
+
+/// Half an ulp of 1.0; the relative rounding error of one operation
+const ROUNDOFF: f64 = f64::EPSILON / 2.0;
+
+/// Error bounds of the floating-point evaluations, from Shewchuk's analysis
+const CCW_ERROR_BOUND: f64 = (3.0 + 16.0 * ROUNDOFF) * ROUNDOFF;
+const ORIENT3D_ERROR_BOUND: f64 = (7.0 + 56.0 * ROUNDOFF) * ROUNDOFF;
+const INCIRCLE_ERROR_BOUND: f64 = (10.0 + 96.0 * ROUNDOFF) * ROUNDOFF;
+
+fn sign(value: f64) -> i8 {
+    if value > 0.0 {
+        1
+    } else if value < 0.0 {
+        -1
+    } else {
+        0
+    }
+}
+
+// Expansion arithmetic: a value is held exactly as a sum of non-overlapping
+// doubles in increasing magnitude, with zero components dropped.
+
+/// `a + b` as a rounded sum and its exact error
+#[inline]
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virtual = x - a;
+    let a_virtual = x - b_virtual;
+    (x, (a - a_virtual) + (b - b_virtual))
+}
+
+/// `a + b` for `|a| >= |b|`
+#[inline]
+fn fast_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    (x, b - (x - a))
+}
+
+/// `a - b` as an exact two-component expansion
+#[inline]
+fn two_diff(a: f64, b: f64) -> Vec<f64> {
+    let x = a - b;
+    let b_virtual = a - x;
+    let a_virtual = x + b_virtual;
+    let y = (a - a_virtual) + (b_virtual - b);
+    if y == 0.0 {
+        vec![x]
+    } else {
+        vec![y, x]
+    }
+}
+
+/// `a * b` as a rounded product and its exact error
+#[inline]
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    (x, a.mul_add(b, -x))
+}
+
+/// Add one double to an expansion
+fn grow_expansion(e: &[f64], b: f64) -> Vec<f64> {
+    let mut out = Vec::with_capacity(e.len() + 1);
+    let mut q = b;
+    for &component in e {
+        let (sum, error) = two_sum(q, component);
+        if error != 0.0 {
+            out.push(error);
+        }
+        q = sum;
+    }
+    if q != 0.0 || out.is_empty() {
+        out.push(q);
+    }
+    out
+}
+
+fn expansion_sum(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter().fold(e.to_vec(), |sum, &component| grow_expansion(&sum, component))
+}
+
+fn expansion_diff(e: &[f64], f: &[f64]) -> Vec<f64> {
+    let negated: Vec<f64> = f.iter().map(|component| -component).collect();
+    expansion_sum(e, &negated)
+}
+
+/// Multiply an expansion by one double
+fn scale_expansion(e: &[f64], b: f64) -> Vec<f64> {
+    let mut out = Vec::with_capacity(e.len() * 2);
+    let Some((&first, rest)) = e.split_first() else {
+        return vec![0.0];
+    };
+    let (mut q, error) = two_product(first, b);
+    if error != 0.0 {
+        out.push(error);
+    }
+    for &component in rest {
+        let (product, product_error) = two_product(component, b);
+        let (sum, error) = two_sum(q, product_error);
+        if error != 0.0 {
+            out.push(error);
+        }
+        let (next, error) = fast_two_sum(product, sum);
+        if error != 0.0 {
+            out.push(error);
+        }
+        q = next;
+    }
+    if q != 0.0 || out.is_empty() {
+        out.push(q);
+    }
+    out
+}
+
+fn expansion_product(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter()
+        .fold(vec![0.0], |sum, &component| expansion_sum(&sum, &scale_expansion(e, component)))
+}
+
+/// The largest component, which carries the sign and approximates the value
+fn most_significant(e: &[f64]) -> f64 {
+    e.iter().rev().copied().find(|c| *c != 0.0).unwrap_or(0.0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -247,4 +533,58 @@ mod tests {
         assert!(remap(5.0, 0.0, 10.0, 0.0, 100.0).approx_eq(&50.0));
         assert!(remap(0.0, -1.0, 1.0, 0.0, 10.0).approx_eq(&5.0));
     }
+
+    #[test]
+    fn test_orientation_predicates() {
+        assert!(orient2d([0.0, 0.0], [1.0, 0.0], [0.0, 1.0]) > 0.0);
+        assert!(orient2d([0.0, 0.0], [0.0, 1.0], [1.0, 0.0]) < 0.0);
+        assert_eq!(orient2d([0.0, 0.0], [1.0, 1.0], [3.0, 3.0]), 0.0);
+
+        // Points on y = x near 0.5 that naive evaluation misjudges: the
+        // exact sign changes with each representable step off the line
+        let (a, b) = ([12.0, 12.0], [24.0, 24.0]);
+        let on = 0.5_f64;
+        let above = [on, f64::from_bits(on.to_bits() + 1)];
+        let below = [on, f64::from_bits(on.to_bits() - 1)];
+        assert_eq!(orient2d(a, b, [on, on]), 0.0);
+        assert!(orient2d(a, b, above) > 0.0);
+        assert!(orient2d(a, b, below) < 0.0);
+
+        let (o, x, y) = ([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        assert!(orient3d(o, x, y, [0.0, 0.0, -1.0]) > 0.0);
+        assert!(orient3d(o, x, y, [0.0, 0.0, 1.0]) < 0.0);
+        assert_eq!(orient3d(o, x, y, [0.3, 0.7, 0.0]), 0.0);
+        let (a3, b3, c3) = ([0.1, 0.2, 0.3], [1.1, 0.7, 0.3], [0.4, 1.9, 0.3]);
+        let lifted = f64::from_bits(0.3_f64.to_bits() + 1);
+        assert_eq!(orient3d(a3, b3, c3, [0.5, 0.5, 0.3]), 0.0);
+        assert!(orient3d(a3, b3, c3, [0.5, 0.5, lifted]) < 0.0);
+    }
+
+    #[test]
+    fn test_incircle_predicate() {
+        let (a, b, c) = ([1.0, 0.0], [0.0, 1.0], [-1.0, 0.0]);
+        assert!(incircle(a, b, c, [0.0, 0.0]) > 0.0);
+        assert!(incircle(a, b, c, [2.0, 0.0]) < 0.0);
+        assert_eq!(incircle(a, b, c, [0.0, -1.0]), 0.0);
+
+        // Cocircular up to the last bit, far from the origin
+        let shift = 1048576.0;
+        let moved = |p: [f64; 2]| [p[0] + shift, p[1] + shift];
+        let (a, b, c) = (moved([3.0, 0.0]), moved([0.0, 3.0]), moved([-3.0, 0.0]));
+        let bottom = moved([0.0, -3.0]);
+        let nudged = [bottom[0], f64::from_bits(bottom[1].to_bits() + 1)];
+        assert_eq!(incircle(a, b, c, bottom), 0.0);
+        assert!(incircle(a, b, c, nudged) > 0.0);
+    }
+
+    #[test]
+    fn test_predicate_mode() {
+        let (a, b) = ([0.0, 0.0], [1.0, 0.0]);
+        let near = [0.5, 1e-12];
+        let tolerance = Tolerance::normal();
+        assert_eq!(orient2d_sign(a, b, near, &tolerance), 0);
+        let exact = tolerance.with_predicates(PredicateMode::Exact);
+        assert_eq!(orient2d_sign(a, b, near, &exact), 1);
+        assert_eq!(tolerance.predicates, PredicateMode::Tolerance);
+    }
 }
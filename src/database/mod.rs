@@ -3,11 +3,12 @@
 //! This module provides a comprehensive database layer with:
 //! - Async connection pooling with health checks
 //! - Query optimization for CAD data patterns
+//! - Typed query builder with bound parameters, pagination and optimistic locking
 //! - Spatial indexing (R-tree and octree)
 //! - Multi-tier caching (L1 memory, L2 disk, L3 distributed)
 //! - Schema migration system
 //! - Master-slave replication support
-//! - Horizontal sharding for large datasets
+//! - Horizontal sharding for large datasets, with tenant-aware routing
 //! - Incremental backup and point-in-time recovery
 //!
 //! ## Architecture
@@ -80,6 +81,14 @@ pub enum DatabaseError {
     #[error("Serialization error: {0}")]
     Serialization(String),
 
+    /// A query builder was asked for an invalid statement
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+
+    /// An optimistically locked row was changed by another writer
+    #[error("Stale version: {table} row is no longer at version {expected}")]
+    StaleVersion { table: String, expected: i64 },
+
     /// I/O error
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
@@ -95,21 +104,28 @@ pub type Result<T> = std::result::Result<T, DatabaseError>;
 // Module declarations
 pub mod connection_pool;
 pub mod query_optimizer;
+pub mod query_builder;
 pub mod spatial_index;
 pub mod cache;
 pub mod migrations;
 pub mod replication;
 pub mod sharding;
+pub mod shard_router;
 pub mod backup;
 
 // Re-exports for convenience
 pub use connection_pool::{ConnectionPool, DatabaseConfig, HealthCheck};
 pub use query_optimizer::{QueryOptimizer, QueryPlan, OptimizationHint};
+pub use query_builder::{Cmp, Delete, Filtered, Insert, Page, Paginated, Select, Update};
 pub use spatial_index::{SpatialIndex, RTreeIndex, OctreeIndex, BoundingVolume};
 pub use cache::{CacheManager, CacheConfig, CacheLayer, CacheStats};
 pub use migrations::{MigrationManager, Migration, MigrationVersion};
 pub use replication::{ReplicationManager, ReplicationConfig, ReplicaRole};
 pub use sharding::{ShardManager, ShardConfig, ShardKey};
+pub use shard_router::{
+    MoveReport, RebalancePlan, ScatterGather, ScatterOptions, ShardNode, ShardRouter, ShardState,
+    ShardTopology, TenantMove, TenantTable, TenantWriter,
+};
 pub use backup::{BackupManager, BackupConfig, BackupType, RestorePoint};
 
 /// Database configuration
@@ -133,6 +149,9 @@ pub struct Config {
     /// Sharding configuration
     pub sharding_config: Option<sharding::ShardConfig>,
 
+    /// Tenant shard topology
+    pub shard_topology: Option<shard_router::ShardTopology>,
+
     /// Backup configuration
     pub backup_config: backup::BackupConfig,
 }
@@ -146,6 +165,7 @@ impl Default for Config {
             cache_config: cache::CacheConfig::default(),
             replication_config: None,
             sharding_config: None,
+            shard_topology: None,
             backup_config: backup::BackupConfig::default(),
         }
     }
@@ -174,6 +194,9 @@ pub struct Database {
     /// Shard manager (optional)
     sharding: Option<ShardManager>,
 
+    /// Tenant shard router (optional)
+    router: Option<ShardRouter>,
+
     /// Backup manager
     backup: BackupManager,
 }
@@ -199,6 +222,12 @@ impl Database {
             None
         };
 
+        let router = if let Some(topology) = config.shard_topology {
+            Some(ShardRouter::connect(topology).await?)
+        } else {
+            None
+        };
+
         let backup = BackupManager::new(config.backup_config)?;
 
         Ok(Self {
@@ -209,6 +238,7 @@ impl Database {
             migrations,
             replication,
             sharding,
+            router,
             backup,
         })
     }
@@ -248,6 +278,11 @@ impl Database {
         self.sharding.as_ref()
     }
 
+    /// Get the tenant shard router
+    pub fn router(&self) -> Option<&ShardRouter> {
+        self.router.as_ref()
+    }
+
     /// Get the backup manager
     pub fn backup(&self) -> &BackupManager {
         &self.backup
@@ -0,0 +1,818 @@
+//! # Typed Query Builder
+//!
+//! Builds `SELECT`, `INSERT`, `UPDATE` and `DELETE` statements for any sqlx
+//! backend, so persistence code stops assembling SQL strings by hand. Every
+//! value is bound as a parameter through [`sqlx::QueryBuilder`], which writes
+//! the placeholder style of the backend (`$1` for PostgreSQL, `?` for
+//! SQLite). Values keep their sqlx type, so custom enums and JSON columns
+//! bind exactly as they would with `sqlx::query(..).bind(..)`.
+//!
+//! Table and column names are written into the SQL as given and must come
+//! from code, never from user input.
+//!
+//! ## Pagination
+//!
+//! [`Page`] turns a 1-based page number into `LIMIT`/`OFFSET`, and
+//! [`Select::fetch_page`] runs the page query and a matching `COUNT(*)` to
+//! return a [`Paginated`] result.
+//!
+//! ## Optimistic Locking
+//!
+//! [`Update::versioned`] names an integer version column and the version the
+//! caller read. The update increments the column and only matches the row if
+//! it still holds that version, so a concurrent writer makes it affect no
+//! rows. [`check_version`] turns that case into
+//! [`DatabaseError::StaleVersion`].
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use caddy::database::query_builder::{check_version, Filtered, Page, Select, Update};
+//! use sqlx::{PgPool, Postgres};
+//! use uuid::Uuid;
+//!
+//! # #[derive(sqlx::FromRow)]
+//! # struct Tenant { id: Uuid }
+//! # async fn example(pool: PgPool, id: Uuid) -> Result<(), Box<dyn std::error::Error>> {
+//! let tenants = Select::<Postgres>::from("tenants")
+//!     .where_null("deleted_at")
+//!     .order_desc("created_at")
+//!     .fetch_page::<Tenant>(&pool, Page::new(2, 25))
+//!     .await?;
+//!
+//! let mut update = Update::<Postgres>::table("tenants")
+//!     .set("name", "Acme")
+//!     .where_eq("id", id)
+//!     .versioned("version", 3)
+//!     .build()?;
+//! let result = update.build().execute(&pool).await?;
+//! check_version(result.rows_affected(), "tenants", 3)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::database::{DatabaseError, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{Database, Encode, FromRow, Pool, QueryBuilder, Type};
+use std::sync::Arc;
+
+/// Default page size
+pub const DEFAULT_PAGE_SIZE: u64 = 50;
+
+/// Largest page size a [`Page`] allows
+pub const MAX_PAGE_SIZE: u64 = 1000;
+
+/// Pushes one bound value; kept behind an `Arc` so a statement can be built
+/// more than once
+type Binder<'args, DB> = Arc<dyn Fn(&mut QueryBuilder<'args, DB>) + Send + Sync + 'args>;
+
+fn binder<'args, DB, T>(value: T) -> Binder<'args, DB>
+where
+    DB: Database,
+    T: 'args + Encode<'args, DB> + Type<DB> + Clone + Send + Sync,
+{
+    Arc::new(move |qb: &mut QueryBuilder<'args, DB>| {
+        qb.push_bind(value.clone());
+    })
+}
+
+/// Comparison operator for a filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Like,
+}
+
+impl Cmp {
+    /// SQL operator
+    pub fn to_sql(&self) -> &'static str {
+        match self {
+            Cmp::Eq => "=",
+            Cmp::Ne => "<>",
+            Cmp::Lt => "<",
+            Cmp::Le => "<=",
+            Cmp::Gt => ">",
+            Cmp::Ge => ">=",
+            Cmp::Like => "LIKE",
+        }
+    }
+}
+
+/// One `WHERE` condition; conditions are joined with `AND`
+pub struct Filter<'args, DB: Database>(Condition<'args, DB>);
+
+enum Condition<'args, DB: Database> {
+    Compare {
+        column: String,
+        cmp: Cmp,
+        value: Binder<'args, DB>,
+    },
+    In {
+        column: String,
+        values: Vec<Binder<'args, DB>>,
+    },
+    IsNull(String),
+    IsNotNull(String),
+}
+
+fn push_filters<'args, DB: Database>(
+    qb: &mut QueryBuilder<'args, DB>,
+    filters: &[Filter<'args, DB>],
+) {
+    for (i, filter) in filters.iter().enumerate() {
+        qb.push(if i == 0 { " WHERE " } else { " AND " });
+        match &filter.0 {
+            Condition::Compare { column, cmp, value } => {
+                qb.push(format!("{} {} ", column, cmp.to_sql()));
+                value(qb);
+            }
+            // An empty list matches nothing, and `IN ()` is not valid SQL
+            Condition::In { values, .. } if values.is_empty() => {
+                qb.push("1 = 0");
+            }
+            Condition::In { column, values } => {
+                qb.push(format!("{} IN (", column));
+                for (j, value) in values.iter().enumerate() {
+                    if j > 0 {
+                        qb.push(", ");
+                    }
+                    value(qb);
+                }
+                qb.push(")");
+            }
+            Condition::IsNull(column) => {
+                qb.push(format!("{} IS NULL", column));
+            }
+            Condition::IsNotNull(column) => {
+                qb.push(format!("{} IS NOT NULL", column));
+            }
+        }
+    }
+}
+
+/// `WHERE` clause methods shared by [`Select`], [`Update`] and [`Delete`]
+pub trait Filtered<'args, DB: Database>: Sized {
+    #[doc(hidden)]
+    fn filters_mut(&mut self) -> &mut Vec<Filter<'args, DB>>;
+
+    /// Add `column <cmp> value`
+    fn where_cmp<T>(mut self, column: impl Into<String>, cmp: Cmp, value: T) -> Self
+    where
+        T: 'args + Encode<'args, DB> + Type<DB> + Clone + Send + Sync,
+    {
+        self.filters_mut().push(Filter(Condition::Compare {
+            column: column.into(),
+            cmp,
+            value: binder(value),
+        }));
+        self
+    }
+
+    /// Add `column = value`
+    fn where_eq<T>(self, column: impl Into<String>, value: T) -> Self
+    where
+        T: 'args + Encode<'args, DB> + Type<DB> + Clone + Send + Sync,
+    {
+        self.where_cmp(column, Cmp::Eq, value)
+    }
+
+    /// Add `column IN (values)`; an empty list matches no rows
+    fn where_in<T>(mut self, column: impl Into<String>, values: impl IntoIterator<Item = T>) -> Self
+    where
+        T: 'args + Encode<'args, DB> + Type<DB> + Clone + Send + Sync,
+    {
+        self.filters_mut().push(Filter(Condition::In {
+            column: column.into(),
+            values: values.into_iter().map(binder).collect(),
+        }));
+        self
+    }
+
+    /// Add `column IS NULL`
+    fn where_null(mut self, column: impl Into<String>) -> Self {
+        self.filters_mut()
+            .push(Filter(Condition::IsNull(column.into())));
+        self
+    }
+
+    /// Add `column IS NOT NULL`
+    fn where_not_null(mut self, column: impl Into<String>) -> Self {
+        self.filters_mut()
+            .push(Filter(Condition::IsNotNull(column.into())));
+        self
+    }
+}
+
+/// A page of results, numbered from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Page {
+    /// Page number, starting at 1
+    pub number: u64,
+
+    /// Rows per page
+    pub per_page: u64,
+}
+
+impl Page {
+    /// Create a page, clamping the number to at least 1 and the size to
+    /// `1..=MAX_PAGE_SIZE`
+    pub fn new(number: u64, per_page: u64) -> Self {
+        Self {
+            number: number.max(1),
+            per_page: per_page.clamp(1, MAX_PAGE_SIZE),
+        }
+    }
+
+    /// Rows to skip
+    pub fn offset(&self) -> u64 {
+        (self.number - 1) * self.per_page
+    }
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Self::new(1, DEFAULT_PAGE_SIZE)
+    }
+}
+
+/// One page of rows together with the total row count
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Paginated<T> {
+    /// Rows on this page
+    pub items: Vec<T>,
+
+    /// The page that was fetched
+    pub page: Page,
+
+    /// Rows matching the query across all pages
+    pub total: u64,
+}
+
+impl<T> Paginated<T> {
+    /// Number of pages; at least 1 so an empty result still has a page
+    pub fn total_pages(&self) -> u64 {
+        self.total.div_ceil(self.page.per_page).max(1)
+    }
+
+    /// Whether a later page exists
+    pub fn has_next(&self) -> bool {
+        self.page.number < self.total_pages()
+    }
+
+    /// Convert the rows, keeping the page and total
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Paginated<U> {
+        Paginated {
+            items: self.items.into_iter().map(f).collect(),
+            page: self.page,
+            total: self.total,
+        }
+    }
+}
+
+/// Fail with [`DatabaseError::StaleVersion`] if a versioned update or delete
+/// affected no rows
+pub fn check_version(rows_affected: u64, table: &str, expected: i64) -> Result<()> {
+    if rows_affected == 0 {
+        return Err(DatabaseError::StaleVersion {
+            table: table.to_string(),
+            expected,
+        });
+    }
+    Ok(())
+}
+
+/// Columns selected by default
+const ALL_COLUMNS: &str = "*";
+
+/// `SELECT` statement
+pub struct Select<'args, DB: Database> {
+    table: String,
+    columns: Vec<String>,
+    filters: Vec<Filter<'args, DB>>,
+    order_by: Vec<(String, bool)>,
+    limit: Option<u64>,
+    offset: u64,
+}
+
+impl<'args, DB: Database> Select<'args, DB> {
+    /// Select all columns from a table
+    pub fn from(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            columns: Vec::new(),
+            filters: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+            offset: 0,
+        }
+    }
+
+    /// Select only these columns
+    pub fn columns(mut self, columns: &[&str]) -> Self {
+        self.columns = columns.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    /// Order by a column, ascending
+    pub fn order_asc(mut self, column: impl Into<String>) -> Self {
+        self.order_by.push((column.into(), false));
+        self
+    }
+
+    /// Order by a column, descending
+    pub fn order_desc(mut self, column: impl Into<String>) -> Self {
+        self.order_by.push((column.into(), true));
+        self
+    }
+
+    /// Return at most `limit` rows
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skip the first `offset` rows
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Restrict to one page of rows
+    pub fn page(self, page: Page) -> Self {
+        self.limit(page.per_page).offset(page.offset())
+    }
+
+    /// Build the statement
+    pub fn build(&self) -> Result<QueryBuilder<'args, DB>> {
+        let columns = if self.columns.is_empty() {
+            ALL_COLUMNS.to_string()
+        } else {
+            self.columns.join(", ")
+        };
+        let mut qb = self.build_with(&columns)?;
+
+        for (i, (column, descending)) in self.order_by.iter().enumerate() {
+            qb.push(if i == 0 { " ORDER BY " } else { ", " });
+            qb.push(column);
+            qb.push(if *descending { " DESC" } else { " ASC" });
+        }
+
+        if let Some(limit) = self.limit {
+            qb.push(format!(" LIMIT {}", limit));
+        }
+        if self.offset > 0 {
+            qb.push(format!(" OFFSET {}", self.offset));
+        }
+
+        Ok(qb)
+    }
+
+    /// Build `SELECT COUNT(*)` over the same rows, ignoring order, limit and
+    /// offset
+    pub fn build_count(&self) -> Result<QueryBuilder<'args, DB>> {
+        self.build_with("COUNT(*)")
+    }
+
+    fn build_with(&self, columns: &str) -> Result<QueryBuilder<'args, DB>> {
+        if self.table.is_empty() {
+            return Err(DatabaseError::InvalidQuery(
+                "SELECT without a table".to_string(),
+            ));
+        }
+
+        let mut qb = QueryBuilder::new(format!("SELECT {} FROM {}", columns, self.table));
+        push_filters(&mut qb, &self.filters);
+        Ok(qb)
+    }
+}
+
+/// `Select::fetch_page` for one backend. sqlx only accepts a backend's
+/// arguments for the lifetime they were created with, which generic code
+/// cannot shorten, so each backend gets its own impl.
+macro_rules! impl_fetch_page {
+    ($db:ty) => {
+        impl<'args> Select<'args, $db> {
+            /// Fetch one page of rows and the total count
+            pub async fn fetch_page<T>(self, pool: &Pool<$db>, page: Page) -> Result<Paginated<T>>
+            where
+                T: for<'r> FromRow<'r, <$db as Database>::Row> + Send + Unpin,
+            {
+                let select = self.page(page);
+
+                let mut count = select.build_count()?;
+                let total: i64 = count.build_query_scalar().fetch_one(pool).await?;
+
+                let mut query = select.build()?;
+                let items = query.build_query_as::<T>().fetch_all(pool).await?;
+
+                Ok(Paginated {
+                    items,
+                    page,
+                    total: total.max(0) as u64,
+                })
+            }
+        }
+    };
+}
+
+impl_fetch_page!(sqlx::Postgres);
+impl_fetch_page!(sqlx::Sqlite);
+
+impl<'args, DB: Database> Filtered<'args, DB> for Select<'args, DB> {
+    fn filters_mut(&mut self) -> &mut Vec<Filter<'args, DB>> {
+        &mut self.filters
+    }
+}
+
+/// `INSERT` statement for one row
+pub struct Insert<'args, DB: Database> {
+    table: String,
+    columns: Vec<String>,
+    values: Vec<Binder<'args, DB>>,
+    returning: Option<String>,
+}
+
+impl<'args, DB: Database> Insert<'args, DB> {
+    /// Insert into a table
+    pub fn into(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            columns: Vec::new(),
+            values: Vec::new(),
+            returning: None,
+        }
+    }
+
+    /// Set a column's value
+    pub fn value<T>(mut self, column: impl Into<String>, value: T) -> Self
+    where
+        T: 'args + Encode<'args, DB> + Type<DB> + Clone + Send + Sync,
+    {
+        self.columns.push(column.into());
+        self.values.push(binder(value));
+        self
+    }
+
+    /// Set a version column to its starting value of 1
+    pub fn versioned(self, column: impl Into<String>) -> Self
+    where
+        i64: 'args + Encode<'args, DB> + Type<DB>,
+    {
+        self.value(column, 1_i64)
+    }
+
+    /// Return the inserted row
+    pub fn returning_all(self) -> Self {
+        self.returning(&[ALL_COLUMNS])
+    }
+
+    /// Return these columns of the inserted row
+    pub fn returning(mut self, columns: &[&str]) -> Self {
+        self.returning = Some(columns.join(", "));
+        self
+    }
+
+    /// Build the statement
+    pub fn build(&self) -> Result<QueryBuilder<'args, DB>> {
+        if self.columns.is_empty() {
+            return Err(DatabaseError::InvalidQuery(format!(
+                "INSERT into {} without values",
+                self.table
+            )));
+        }
+
+        let mut qb = QueryBuilder::new(format!(
+            "INSERT INTO {} ({}) VALUES (",
+            self.table,
+            self.columns.join(", ")
+        ));
+        for (i, value) in self.values.iter().enumerate() {
+            if i > 0 {
+                qb.push(", ");
+            }
+            value(&mut qb);
+        }
+        qb.push(")");
+
+        if let Some(returning) = &self.returning {
+            qb.push(format!(" RETURNING {}", returning));
+        }
+
+        Ok(qb)
+    }
+}
+
+/// How an [`Update`] assigns a column
+enum Assignment<'args, DB: Database> {
+    Value(Binder<'args, DB>),
+    Null,
+    Increment,
+}
+
+/// `UPDATE` statement
+pub struct Update<'args, DB: Database> {
+    table: String,
+    assignments: Vec<(String, Assignment<'args, DB>)>,
+    filters: Vec<Filter<'args, DB>>,
+    returning: Option<String>,
+}
+
+impl<'args, DB: Database> Update<'args, DB> {
+    /// Update rows of a table
+    pub fn table(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            assignments: Vec::new(),
+            filters: Vec::new(),
+            returning: None,
+        }
+    }
+
+    /// Set a column's value
+    pub fn set<T>(mut self, column: impl Into<String>, value: T) -> Self
+    where
+        T: 'args + Encode<'args, DB> + Type<DB> + Clone + Send + Sync,
+    {
+        self.assignments
+            .push((column.into(), Assignment::Value(binder(value))));
+        self
+    }
+
+    /// Set a column to `NULL`
+    pub fn set_null(mut self, column: impl Into<String>) -> Self {
+        self.assignments.push((column.into(), Assignment::Null));
+        self
+    }
+
+    /// Only update the row if `column` still holds `expected`, and increment
+    /// it. Check the outcome with [`check_version`].
+    pub fn versioned(mut self, column: impl Into<String>, expected: i64) -> Self
+    where
+        i64: 'args + Encode<'args, DB> + Type<DB>,
+    {
+        let column = column.into();
+        self.assignments
+            .push((column.clone(), Assignment::Increment));
+        self.where_eq(column, expected)
+    }
+
+    /// Return the updated rows
+    pub fn returning_all(self) -> Self {
+        self.returning(&[ALL_COLUMNS])
+    }
+
+    /// Return these columns of the updated rows
+    pub fn returning(mut self, columns: &[&str]) -> Self {
+        self.returning = Some(columns.join(", "));
+        self
+    }
+
+    /// Build the statement. An update without a filter is refused, since
+    /// it would rewrite the whole table.
+    pub fn build(&self) -> Result<QueryBuilder<'args, DB>> {
+        if self.assignments.is_empty() {
+            return Err(DatabaseError::InvalidQuery(format!(
+                "UPDATE of {} without assignments",
+                self.table
+            )));
+        }
+        if self.filters.is_empty() {
+            return Err(DatabaseError::InvalidQuery(format!(
+                "UPDATE of {} without a filter",
+                self.table
+            )));
+        }
+
+        let mut qb = QueryBuilder::new(format!("UPDATE {} SET ", self.table));
+        for (i, (column, assignment)) in self.assignments.iter().enumerate() {
+            if i > 0 {
+                qb.push(", ");
+            }
+            match assignment {
+                Assignment::Value(value) => {
+                    qb.push(format!("{} = ", column));
+                    value(&mut qb);
+                }
+                Assignment::Null => {
+                    qb.push(format!("{} = NULL", column));
+                }
+                Assignment::Increment => {
+                    qb.push(format!("{0} = {0} + 1", column));
+                }
+            }
+        }
+        push_filters(&mut qb, &self.filters);
+
+        if let Some(returning) = &self.returning {
+            qb.push(format!(" RETURNING {}", returning));
+        }
+
+        Ok(qb)
+    }
+}
+
+impl<'args, DB: Database> Filtered<'args, DB> for Update<'args, DB> {
+    fn filters_mut(&mut self) -> &mut Vec<Filter<'args, DB>> {
+        &mut self.filters
+    }
+}
+
+/// `DELETE` statement
+pub struct Delete<'args, DB: Database> {
+    table: String,
+    filters: Vec<Filter<'args, DB>>,
+}
+
+impl<'args, DB: Database> Delete<'args, DB> {
+    /// Delete rows from a table
+    pub fn from(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            filters: Vec::new(),
+        }
+    }
+
+    /// Only delete the row if `column` still holds `expected`. Check the
+    /// outcome with [`check_version`].
+    pub fn versioned(self, column: impl Into<String>, expected: i64) -> Self
+    where
+        i64: 'args + Encode<'args, DB> + Type<DB>,
+    {
+        self.where_eq(column, expected)
+    }
+
+    /// Build the statement. A delete without a filter is refused, since it
+    /// would empty the table.
+    pub fn build(&self) -> Result<QueryBuilder<'args, DB>> {
+        if self.filters.is_empty() {
+            return Err(DatabaseError::InvalidQuery(format!(
+                "DELETE from {} without a filter",
+                self.table
+            )));
+        }
+
+        let mut qb = QueryBuilder::new(format!("DELETE FROM {}", self.table));
+        push_filters(&mut qb, &self.filters);
+        Ok(qb)
+    }
+}
+
+impl<'args, DB: Database> Filtered<'args, DB> for Delete<'args, DB> {
+    fn filters_mut(&mut self) -> &mut Vec<Filter<'args, DB>> {
+        &mut self.filters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::{Sqlite, SqlitePool};
+
+    #[test]
+    fn test_statement_sql() {
+        let select = Select::<Sqlite>::from("tenants")
+            .columns(&["id", "slug"])
+            .where_eq("status", "active")
+            .where_in("plan", ["pro", "enterprise"])
+            .where_null("deleted_at")
+            .order_desc("created_at")
+            .page(Page::new(3, 20));
+        assert_eq!(
+            select.build().unwrap().sql(),
+            "SELECT id, slug FROM tenants WHERE status = ? AND plan IN (?, ?) \
+             AND deleted_at IS NULL ORDER BY created_at DESC LIMIT 20 OFFSET 40"
+        );
+        assert_eq!(
+            select.build_count().unwrap().sql(),
+            "SELECT COUNT(*) FROM tenants WHERE status = ? AND plan IN (?, ?) AND deleted_at IS NULL"
+        );
+
+        let insert = Insert::<Sqlite>::into("tenants")
+            .value("id", 1_i64)
+            .value("slug", "acme")
+            .versioned("version")
+            .returning_all();
+        assert_eq!(
+            insert.build().unwrap().sql(),
+            "INSERT INTO tenants (id, slug, version) VALUES (?, ?, ?) RETURNING *"
+        );
+
+        let update = Update::<Sqlite>::table("tenants")
+            .set("slug", "acme-corp")
+            .set_null("custom_domain")
+            .where_eq("id", 1_i64)
+            .versioned("version", 4);
+        assert_eq!(
+            update.build().unwrap().sql(),
+            "UPDATE tenants SET slug = ?, custom_domain = NULL, version = version + 1 \
+             WHERE id = ? AND version = ?"
+        );
+
+        let empty = Select::<Sqlite>::from("tenants").where_in("id", Vec::<i64>::new());
+        assert!(empty.build().unwrap().sql().ends_with("WHERE 1 = 0"));
+
+        assert!(Update::<Sqlite>::table("tenants")
+            .set("slug", "x")
+            .build()
+            .is_err());
+        assert!(Delete::<Sqlite>::from("tenants").build().is_err());
+        assert!(Insert::<Sqlite>::into("tenants").build().is_err());
+    }
+
+    #[test]
+    fn test_page_math() {
+        assert_eq!(Page::new(0, 0), Page::new(1, 1));
+        assert_eq!(Page::new(2, 5000).per_page, MAX_PAGE_SIZE);
+        assert_eq!(Page::new(3, 25).offset(), 50);
+
+        let page = Paginated {
+            items: vec![1, 2],
+            page: Page::new(2, 2),
+            total: 5,
+        };
+        assert_eq!(page.total_pages(), 3);
+        assert!(page.has_next());
+        assert_eq!(page.map(|i| i * 10).items, vec![10, 20]);
+    }
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+        id: i64,
+        name: String,
+        version: i64,
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_with_optimistic_locking() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT NOT NULL, version INTEGER NOT NULL)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        for i in 1..=7_i64 {
+            let mut insert = Insert::<Sqlite>::into("items")
+                .value("id", i)
+                .value("name", format!("item-{}", i))
+                .versioned("version")
+                .returning_all()
+                .build()
+                .unwrap();
+            let row: Row = insert.build_query_as().fetch_one(&pool).await.unwrap();
+            assert_eq!((row.id, row.version), (i, 1));
+        }
+
+        let page = Select::<Sqlite>::from("items")
+            .where_cmp("id", Cmp::Gt, 1_i64)
+            .order_asc("id")
+            .fetch_page::<Row>(&pool, Page::new(2, 4))
+            .await
+            .unwrap();
+        assert_eq!(page.total, 6);
+        assert_eq!(
+            page.items.iter().map(|r| r.id).collect::<Vec<_>>(),
+            vec![6, 7]
+        );
+        assert!(!page.has_next());
+
+        let rename = |name: &'static str| {
+            Update::<Sqlite>::table("items")
+                .set("name", name)
+                .where_eq("id", 3_i64)
+                .versioned("version", 1)
+                .build()
+                .unwrap()
+        };
+        let first = rename("first").build().execute(&pool).await.unwrap();
+        assert!(check_version(first.rows_affected(), "items", 1).is_ok());
+
+        let second = rename("second").build().execute(&pool).await.unwrap();
+        assert!(matches!(
+            check_version(second.rows_affected(), "items", 1),
+            Err(DatabaseError::StaleVersion { expected: 1, .. })
+        ));
+
+        let mut fetch = Select::<Sqlite>::from("items")
+            .where_eq("id", 3_i64)
+            .build()
+            .unwrap();
+        let row: Row = fetch.build_query_as().fetch_one(&pool).await.unwrap();
+        assert_eq!((row.name.as_str(), row.version), ("first", 2));
+
+        let deleted = Delete::<Sqlite>::from("items")
+            .where_eq("id", 3_i64)
+            .versioned("version", 2)
+            .build()
+            .unwrap()
+            .build()
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(deleted.rows_affected(), 1);
+    }
+}
@@ -0,0 +1,1009 @@
+//! # Tenant Shard Router
+//!
+//! Routes each tenant to a single shard, so all of a tenant's rows live in
+//! one database and tenant queries never fan out.
+//!
+//! The first time a tenant is seen it is placed by a consistent-hash ring
+//! over the active shards, weighted by capacity, unless the topology pins
+//! it to a shard. The placement is then recorded in the tenant directory
+//! and followed from then on. Changing the topology therefore never moves
+//! data implicitly. [`ShardRouter::plan_rebalance`] lists the tenants whose
+//! ring shard has changed, for example after a shard is added, reweighted
+//! or set to drain, and [`ShardRouter::move_tenant`] moves one online.
+//! Other tenants are unaffected, and the moving tenant keeps reading from
+//! its old shard until the directory is switched; only its writes pause
+//! while its rows are copied.
+//!
+//! Cross-shard admin queries go through [`ShardRouter::scatter_gather`],
+//! which runs a query on every shard concurrently and collects the rows
+//! per shard.
+
+use crate::database::connection_pool::{ConnectionPool, DatabaseConfig};
+use crate::database::sharding::ShardKey;
+use crate::database::{DatabaseError, Result};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedRwLockReadGuard, RwLock as TokioRwLock};
+
+/// Shard lifecycle state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShardState {
+    /// Serves its tenants and takes new ones
+    #[default]
+    Active,
+
+    /// Serves its tenants but takes no new ones; rebalancing moves them off
+    Draining,
+
+    /// Out of service; its tenants cannot be reached
+    Offline,
+}
+
+/// One shard in the topology
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardNode {
+    /// Stable shard ID, recorded in the tenant directory
+    pub id: u32,
+
+    /// Connection URL
+    pub url: String,
+
+    /// Relative capacity; a shard of weight 2 takes twice the tenants
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+
+    /// Lifecycle state
+    #[serde(default)]
+    pub state: ShardState,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+impl ShardNode {
+    /// Active shard of weight 1
+    pub fn new(id: u32, url: impl Into<String>) -> Self {
+        Self {
+            id,
+            url: url.into(),
+            weight: default_weight(),
+            state: ShardState::Active,
+        }
+    }
+
+    /// Set the weight
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Set the state
+    pub fn with_state(mut self, state: ShardState) -> Self {
+        self.state = state;
+        self
+    }
+}
+
+/// Shard topology configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardTopology {
+    /// Configuration version, bumped on every change
+    #[serde(default)]
+    pub version: u64,
+
+    /// Shards
+    pub shards: Vec<ShardNode>,
+
+    /// Ring points per unit of shard weight
+    #[serde(default = "default_virtual_nodes")]
+    pub virtual_nodes: u32,
+
+    /// Tenants pinned to a shard regardless of the ring (e.g. dedicated shards)
+    #[serde(default)]
+    pub pinned: BTreeMap<String, u32>,
+}
+
+fn default_virtual_nodes() -> u32 {
+    150
+}
+
+impl ShardTopology {
+    /// Topology over the given shards
+    pub fn new(shards: Vec<ShardNode>) -> Self {
+        Self {
+            version: 1,
+            shards,
+            virtual_nodes: default_virtual_nodes(),
+            pinned: BTreeMap::new(),
+        }
+    }
+
+    /// Pin a tenant to a shard
+    pub fn pin(mut self, tenant_id: impl Into<String>, shard: u32) -> Self {
+        self.pinned.insert(tenant_id.into(), shard);
+        self
+    }
+
+    /// Parse and validate a topology from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        let topology: Self = serde_json::from_str(json).map_err(|e| DatabaseError::Serialization(e.to_string()))?;
+        topology.validate()?;
+        Ok(topology)
+    }
+
+    /// Serialize to JSON
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| DatabaseError::Serialization(e.to_string()))
+    }
+
+    /// Check shard IDs are unique, pins name known shards and some shard
+    /// can take new tenants
+    pub fn validate(&self) -> Result<()> {
+        let mut ids = HashSet::new();
+        for shard in &self.shards {
+            if !ids.insert(shard.id) {
+                return Err(DatabaseError::Sharding(format!("Duplicate shard ID {}", shard.id)));
+            }
+        }
+        if let Some((tenant, shard)) = self.pinned.iter().find(|(_, shard)| !ids.contains(shard)) {
+            return Err(DatabaseError::Sharding(format!(
+                "Tenant {} is pinned to unknown shard {}",
+                tenant, shard
+            )));
+        }
+        if self.virtual_nodes == 0 || !self.shards.iter().any(|s| s.state == ShardState::Active && s.weight > 0) {
+            return Err(DatabaseError::Sharding("No active shard can take tenants".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Get a shard by ID
+    pub fn shard(&self, id: u32) -> Option<&ShardNode> {
+        self.shards.iter().find(|s| s.id == id)
+    }
+
+    /// Consistent-hash ring over the active shards
+    fn ring(&self) -> BTreeMap<u64, u32> {
+        let mut ring = BTreeMap::new();
+        for shard in self.shards.iter().filter(|s| s.state == ShardState::Active) {
+            for i in 0..shard.weight as u64 * self.virtual_nodes as u64 {
+                ring.insert(stable_hash(format!("shard-{}#{}", shard.id, i).as_bytes()), shard.id);
+            }
+        }
+        ring
+    }
+}
+
+/// Router state guarded by one lock so the ring always matches the topology
+struct RouterState {
+    topology: ShardTopology,
+    ring: BTreeMap<u64, u32>,
+    directory: BTreeMap<String, u32>,
+}
+
+impl RouterState {
+    /// Shard a tenant belongs on under the current topology
+    fn target(&self, tenant_id: &str) -> Result<u32> {
+        if let Some(&shard) = self.topology.pinned.get(tenant_id) {
+            return Ok(shard);
+        }
+        let hash = stable_hash(tenant_id.as_bytes());
+        self.ring
+            .range(hash..)
+            .next()
+            .or_else(|| self.ring.iter().next())
+            .map(|(_, &shard)| shard)
+            .ok_or_else(|| DatabaseError::Sharding("Hash ring is empty".to_string()))
+    }
+}
+
+/// Router statistics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouterStats {
+    /// Tenant lookups
+    pub lookups: u64,
+
+    /// Tenants placed for the first time
+    pub placements: u64,
+
+    /// Writes refused because the tenant was being moved
+    pub writes_refused: u64,
+
+    /// Scatter-gather queries
+    pub scatter_queries: u64,
+
+    /// Tenants moved between shards
+    pub tenants_moved: u64,
+}
+
+/// Tenant-aware shard router
+pub struct ShardRouter {
+    /// Topology, ring and tenant directory
+    state: RwLock<RouterState>,
+
+    /// Connection pools by shard ID
+    pools: RwLock<HashMap<u32, Arc<ConnectionPool>>>,
+
+    /// Per-tenant write leases; a move takes the lease exclusively
+    leases: Mutex<HashMap<String, Arc<TokioRwLock<()>>>>,
+
+    /// Statistics
+    stats: RwLock<RouterStats>,
+}
+
+impl ShardRouter {
+    /// Validate the topology and connect to every shard that is not offline
+    pub async fn connect(topology: ShardTopology) -> Result<Self> {
+        topology.validate()?;
+        let mut pools = HashMap::new();
+        for shard in topology.shards.iter().filter(|s| s.state != ShardState::Offline) {
+            pools.insert(shard.id, Arc::new(open_pool(&shard.url).await?));
+        }
+        Self::with_pools(topology, pools)
+    }
+
+    /// Router over already-open pools
+    pub fn with_pools(topology: ShardTopology, pools: HashMap<u32, Arc<ConnectionPool>>) -> Result<Self> {
+        topology.validate()?;
+        let ring = topology.ring();
+        Ok(Self {
+            state: RwLock::new(RouterState {
+                topology,
+                ring,
+                directory: BTreeMap::new(),
+            }),
+            pools: RwLock::new(pools),
+            leases: Mutex::new(HashMap::new()),
+            stats: RwLock::new(RouterStats::default()),
+        })
+    }
+
+    /// Current topology
+    pub fn topology(&self) -> ShardTopology {
+        self.state.read().topology.clone()
+    }
+
+    /// Replace the topology
+    ///
+    /// Tenants stay where the directory has them; plan a rebalance to move
+    /// them to their new shards. Fails if a shard that still holds tenants
+    /// would be removed.
+    pub async fn apply_topology(&self, mut topology: ShardTopology) -> Result<()> {
+        topology.validate()?;
+        {
+            let state = self.state.read();
+            if let Some((tenant, shard)) = state.directory.iter().find(|(_, &id)| topology.shard(id).is_none()) {
+                return Err(DatabaseError::Sharding(format!(
+                    "Shard {} still holds tenant {}; move its tenants before removing it",
+                    shard, tenant
+                )));
+            }
+            topology.version = topology.version.max(state.topology.version + 1);
+        }
+
+        let known: HashSet<u32> = self.pools.read().keys().copied().collect();
+        for shard in topology.shards.iter().filter(|s| s.state != ShardState::Offline && !known.contains(&s.id)) {
+            let pool = Arc::new(open_pool(&shard.url).await?);
+            self.pools.write().insert(shard.id, pool);
+        }
+
+        let mut state = self.state.write();
+        state.ring = topology.ring();
+        state.topology = topology;
+        log::info!("Applied shard topology version {}", state.topology.version);
+        Ok(())
+    }
+
+    /// Shard holding a tenant, placing the tenant if it is new
+    pub fn shard_for(&self, tenant_id: &str) -> Result<u32> {
+        self.stats.write().lookups += 1;
+        if let Some(&shard) = self.state.read().directory.get(tenant_id) {
+            return Ok(shard);
+        }
+
+        let mut state = self.state.write();
+        if let Some(&shard) = state.directory.get(tenant_id) {
+            return Ok(shard);
+        }
+        let shard = state.target(tenant_id)?;
+        state.directory.insert(tenant_id.to_string(), shard);
+        self.stats.write().placements += 1;
+        log::debug!("Placed tenant {} on shard {}", tenant_id, shard);
+        Ok(shard)
+    }
+
+    /// Shard for a [`ShardKey::Tenant`] key
+    pub fn route(&self, key: &ShardKey) -> Result<u32> {
+        let tenant_id = key
+            .tenant_id()
+            .ok_or_else(|| DatabaseError::Sharding("Shard router requires tenant keys".to_string()))?;
+        self.shard_for(tenant_id)
+    }
+
+    /// Pool for reading a tenant's rows
+    pub fn reader(&self, tenant_id: &str) -> Result<Arc<ConnectionPool>> {
+        let shard = self.shard_for(tenant_id)?;
+        self.pool(shard)
+    }
+
+    /// Pool for writing a tenant's rows
+    ///
+    /// Fails while the tenant is being moved; hold the returned writer for
+    /// the duration of the write so a move waits for it to finish.
+    pub fn writer(&self, tenant_id: &str) -> Result<TenantWriter> {
+        let lease = self.lease(tenant_id).try_read_owned().map_err(|_| {
+            self.stats.write().writes_refused += 1;
+            DatabaseError::Sharding(format!("Tenant {} is being moved; retry shortly", tenant_id))
+        })?;
+        let shard = self.shard_for(tenant_id)?;
+        Ok(TenantWriter {
+            shard,
+            pool: self.pool(shard)?,
+            _lease: lease,
+        })
+    }
+
+    /// Snapshot of the tenant directory, for persisting
+    pub fn directory(&self) -> BTreeMap<String, u32> {
+        self.state.read().directory.clone()
+    }
+
+    /// Restore a persisted tenant directory
+    pub fn load_directory(&self, directory: BTreeMap<String, u32>) -> Result<()> {
+        let mut state = self.state.write();
+        if let Some((tenant, shard)) = directory.iter().find(|(_, &id)| state.topology.shard(id).is_none()) {
+            return Err(DatabaseError::Sharding(format!(
+                "Tenant {} is placed on unknown shard {}",
+                tenant, shard
+            )));
+        }
+        state.directory = directory;
+        Ok(())
+    }
+
+    /// Run a read-only admin query on every shard and gather the rows
+    ///
+    /// Shards are queried concurrently. Offline, failing and timed-out
+    /// shards are listed in [`ScatterGather::failures`] when
+    /// `options.allow_partial` is set, and fail the whole query otherwise.
+    pub async fn scatter_gather<O>(&self, sql: &str, options: &ScatterOptions) -> Result<ScatterGather<O>>
+    where
+        O: for<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
+    {
+        self.stats.write().scatter_queries += 1;
+        let shards: Vec<u32> = self.state.read().topology.shards.iter().map(|s| s.id).collect();
+        let pools = self.pools.read().clone();
+
+        let queries = shards.into_iter().map(|shard| {
+            let pool = pools.get(&shard).cloned();
+            async move {
+                let Some(pool) = pool else {
+                    return (shard, Err("shard is offline".to_string()));
+                };
+                let query = sqlx::query_as::<_, O>(sql).fetch_all(pool.inner());
+                match tokio::time::timeout(options.timeout, query).await {
+                    Ok(Ok(rows)) => (shard, Ok(rows)),
+                    Ok(Err(e)) => (shard, Err(e.to_string())),
+                    Err(_) => (shard, Err(format!("timed out after {:?}", options.timeout))),
+                }
+            }
+        });
+
+        let mut gathered = ScatterGather {
+            results: Vec::new(),
+            failures: Vec::new(),
+        };
+        for (shard, outcome) in futures::future::join_all(queries).await {
+            match outcome {
+                Ok(rows) => gathered.results.push(ShardRows { shard, rows }),
+                Err(error) => gathered.failures.push((shard, error)),
+            }
+        }
+
+        if !options.allow_partial {
+            if let Some((shard, error)) = gathered.failures.first() {
+                return Err(DatabaseError::Sharding(format!("Shard {} failed: {}", shard, error)));
+            }
+        }
+        Ok(gathered)
+    }
+
+    /// Tenants not on the shard the current topology puts them on, e.g.
+    /// after adding or reweighting shards or draining one
+    ///
+    /// With consistent hashing only the tenants claimed by the changed
+    /// shards move. Moves are ordered by tenant ID.
+    pub fn plan_rebalance(&self) -> Result<RebalancePlan> {
+        let state = self.state.read();
+        let mut moves = Vec::new();
+        for (tenant, &from) in &state.directory {
+            let to = state.target(tenant)?;
+            if to != from {
+                moves.push(TenantMove {
+                    tenant_id: tenant.clone(),
+                    from,
+                    to,
+                });
+            }
+        }
+        Ok(RebalancePlan { moves })
+    }
+
+    /// Carry out a rebalance plan one tenant at a time, stopping at the
+    /// first failure
+    pub async fn rebalance(&self, plan: &RebalancePlan, tables: &[TenantTable]) -> Result<Vec<MoveReport>> {
+        let mut reports = Vec::with_capacity(plan.moves.len());
+        for tenant_move in &plan.moves {
+            reports.push(self.move_tenant(tenant_move, tables).await?);
+        }
+        Ok(reports)
+    }
+
+    /// Move a tenant's rows to another shard while both stay online
+    ///
+    /// Waits for the tenant's in-flight writes and refuses new ones, copies
+    /// its rows from each table into the target in one transaction, checks
+    /// the row counts match, switches the directory and lets writes resume
+    /// before removing the rows from the source. Reads keep going to the
+    /// source until the switch. Both shards must be SQLite files, as the
+    /// copy attaches the source to a target connection.
+    pub async fn move_tenant(&self, tenant_move: &TenantMove, tables: &[TenantTable]) -> Result<MoveReport> {
+        let TenantMove { tenant_id, from, to } = tenant_move;
+        for table in tables {
+            table.check()?;
+        }
+        if self.shard_for(tenant_id)? != *from {
+            return Err(DatabaseError::Sharding(format!(
+                "Tenant {} is no longer on shard {}",
+                tenant_id, from
+            )));
+        }
+        let source_path = {
+            let state = self.state.read();
+            let source = state
+                .topology
+                .shard(*from)
+                .ok_or_else(|| DatabaseError::Sharding(format!("Shard {} not found", from)))?;
+            sqlite_path(&source.url)?
+        };
+        let (source, target) = (self.pool(*from)?, self.pool(*to)?);
+
+        let lease = self.lease(tenant_id);
+        let paused = Instant::now();
+        let exclusive = lease.write_owned().await;
+        let rows_copied = copy_tenant(&target, &source_path, tenant_id, tables).await?;
+        self.state.write().directory.insert(tenant_id.clone(), *to);
+        drop(exclusive);
+        let write_pause = paused.elapsed();
+
+        for table in tables {
+            let sql = format!("DELETE FROM \"{}\" WHERE \"{}\" = ?", table.name, table.tenant_column);
+            sqlx::query(&sql).bind(tenant_id.as_str()).execute(source.inner()).await?;
+        }
+
+        self.stats.write().tenants_moved += 1;
+        log::info!(
+            "Moved tenant {} from shard {} to shard {} ({} rows, writes paused {:?})",
+            tenant_id,
+            from,
+            to,
+            rows_copied,
+            write_pause
+        );
+
+        Ok(MoveReport {
+            tenant_id: tenant_id.clone(),
+            from: *from,
+            to: *to,
+            rows_copied,
+            write_pause,
+        })
+    }
+
+    /// Get router statistics
+    pub fn stats(&self) -> RouterStats {
+        self.stats.read().clone()
+    }
+
+    fn pool(&self, shard: u32) -> Result<Arc<ConnectionPool>> {
+        let state = self.state.read();
+        let node = state
+            .topology
+            .shard(shard)
+            .ok_or_else(|| DatabaseError::Sharding(format!("Shard {} not found", shard)))?;
+        if node.state == ShardState::Offline {
+            return Err(DatabaseError::Sharding(format!("Shard {} is offline", shard)));
+        }
+        self.pools
+            .read()
+            .get(&shard)
+            .cloned()
+            .ok_or_else(|| DatabaseError::Sharding(format!("Shard {} is not connected", shard)))
+    }
+
+    fn lease(&self, tenant_id: &str) -> Arc<TokioRwLock<()>> {
+        self.leases.lock().entry(tenant_id.to_string()).or_default().clone()
+    }
+}
+
+/// Write access to a tenant's shard; a move of the tenant waits until it
+/// is dropped
+pub struct TenantWriter {
+    shard: u32,
+    pool: Arc<ConnectionPool>,
+    _lease: OwnedRwLockReadGuard<()>,
+}
+
+impl TenantWriter {
+    /// Shard written to
+    pub fn shard(&self) -> u32 {
+        self.shard
+    }
+}
+
+impl Deref for TenantWriter {
+    type Target = ConnectionPool;
+
+    fn deref(&self) -> &ConnectionPool {
+        &self.pool
+    }
+}
+
+/// Scatter-gather options
+#[derive(Debug, Clone)]
+pub struct ScatterOptions {
+    /// Return the shards that answered instead of failing
+    pub allow_partial: bool,
+
+    /// Per-shard timeout
+    pub timeout: Duration,
+}
+
+impl Default for ScatterOptions {
+    fn default() -> Self {
+        Self {
+            allow_partial: false,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Rows returned by one shard
+#[derive(Debug, Clone)]
+pub struct ShardRows<O> {
+    /// Shard ID
+    pub shard: u32,
+
+    /// Rows
+    pub rows: Vec<O>,
+}
+
+/// Result of a scatter-gather query
+#[derive(Debug, Clone)]
+pub struct ScatterGather<O> {
+    /// Rows from each shard that answered
+    pub results: Vec<ShardRows<O>>,
+
+    /// Shards that did not answer, with the reason
+    pub failures: Vec<(u32, String)>,
+}
+
+impl<O> ScatterGather<O> {
+    /// Whether every shard answered
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// All rows, in shard order
+    pub fn rows(&self) -> impl Iterator<Item = &O> {
+        self.results.iter().flat_map(|r| r.rows.iter())
+    }
+
+    /// Take all rows, in shard order
+    pub fn into_rows(self) -> Vec<O> {
+        self.results.into_iter().flat_map(|r| r.rows).collect()
+    }
+}
+
+/// A table holding tenant rows
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenantTable {
+    /// Table name
+    pub name: String,
+
+    /// Column holding the tenant ID
+    pub tenant_column: String,
+}
+
+impl TenantTable {
+    /// Table keyed by a tenant column
+    pub fn new(name: impl Into<String>, tenant_column: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            tenant_column: tenant_column.into(),
+        }
+    }
+
+    /// Names are spliced into SQL, so only plain identifiers are accepted
+    fn check(&self) -> Result<()> {
+        let plain = |s: &str| {
+            !s.is_empty()
+                && !s.starts_with(|c: char| c.is_ascii_digit())
+                && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        };
+        if plain(&self.name) && plain(&self.tenant_column) {
+            Ok(())
+        } else {
+            Err(DatabaseError::Sharding(format!(
+                "Invalid table or column name: {}.{}",
+                self.name, self.tenant_column
+            )))
+        }
+    }
+}
+
+/// A tenant to relocate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenantMove {
+    /// Tenant ID
+    pub tenant_id: String,
+
+    /// Shard it is on
+    pub from: u32,
+
+    /// Shard it belongs on
+    pub to: u32,
+}
+
+/// Tenant moves that bring the directory in line with the topology
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RebalancePlan {
+    /// Moves, in the order they will be made
+    pub moves: Vec<TenantMove>,
+}
+
+impl RebalancePlan {
+    /// Whether every tenant is already in place
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Number of tenants leaving and arriving at each shard
+    pub fn shard_changes(&self) -> BTreeMap<u32, (usize, usize)> {
+        let mut changes = BTreeMap::new();
+        for tenant_move in &self.moves {
+            changes.entry(tenant_move.from).or_insert((0, 0)).0 += 1;
+            changes.entry(tenant_move.to).or_insert((0, 0)).1 += 1;
+        }
+        changes
+    }
+}
+
+/// Outcome of a tenant move
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveReport {
+    /// Tenant ID
+    pub tenant_id: String,
+
+    /// Source shard
+    pub from: u32,
+
+    /// Target shard
+    pub to: u32,
+
+    /// Rows copied across all tables
+    pub rows_copied: u64,
+
+    /// How long the tenant's writes were paused
+    pub write_pause: Duration,
+}
+
+/// Copy a tenant's rows into `target` from the SQLite file at
+/// `source_path`, replacing any rows already there
+async fn copy_tenant(target: &ConnectionPool, source_path: &str, tenant_id: &str, tables: &[TenantTable]) -> Result<u64> {
+    let mut conn = target.inner().acquire().await?;
+    sqlx::query("ATTACH DATABASE ? AS shard_source")
+        .bind(source_path)
+        .execute(&mut *conn)
+        .await?;
+
+    let copied = async {
+        sqlx::query("BEGIN IMMEDIATE").execute(&mut *conn).await?;
+        let mut rows = 0u64;
+        for table in tables {
+            let (name, column) = (&table.name, &table.tenant_column);
+            sqlx::query(&format!("DELETE FROM main.\"{}\" WHERE \"{}\" = ?", name, column))
+                .bind(tenant_id)
+                .execute(&mut *conn)
+                .await?;
+            let inserted = sqlx::query(&format!(
+                "INSERT INTO main.\"{0}\" SELECT * FROM shard_source.\"{0}\" WHERE \"{1}\" = ?",
+                name, column
+            ))
+            .bind(tenant_id)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+            let (expected,): (i64,) = sqlx::query_as(&format!(
+                "SELECT COUNT(*) FROM shard_source.\"{}\" WHERE \"{}\" = ?",
+                name, column
+            ))
+            .bind(tenant_id)
+            .fetch_one(&mut *conn)
+            .await?;
+            if inserted != expected as u64 {
+                return Err(DatabaseError::Sharding(format!(
+                    "Copied {} of {} rows of {} for tenant {}",
+                    inserted, expected, name, tenant_id
+                )));
+            }
+            rows += inserted;
+        }
+        sqlx::query("COMMIT").execute(&mut *conn).await?;
+        Ok(rows)
+    }
+    .await;
+
+    if copied.is_err() {
+        let _ = sqlx::query("ROLLBACK").execute(&mut *conn).await;
+    }
+    sqlx::query("DETACH DATABASE shard_source").execute(&mut *conn).await?;
+    copied
+}
+
+async fn open_pool(url: &str) -> Result<ConnectionPool> {
+    ConnectionPool::new(DatabaseConfig {
+        url: url.to_string(),
+        ..Default::default()
+    })
+    .await
+}
+
+/// File behind a SQLite shard URL
+fn sqlite_path(url: &str) -> Result<String> {
+    let path = url.strip_prefix("sqlite://").unwrap_or(url);
+    if path.is_empty() || path.contains(":memory:") {
+        return Err(DatabaseError::Sharding(format!(
+            "Shard {} is not a SQLite file and cannot be moved from",
+            url
+        )));
+    }
+    Ok(path.to_string())
+}
+
+/// FNV-1a with a final mix; unlike `DefaultHasher` it is fixed across
+/// builds and platforms, so placements computed today hold after upgrades
+fn stable_hash(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash ^= hash >> 30;
+    hash = hash.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash ^= hash >> 27;
+    hash = hash.wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topology(shards: u32) -> ShardTopology {
+        ShardTopology::new((0..shards).map(|id| ShardNode::new(id, format!("sqlite://shard{}.db", id))).collect())
+    }
+
+    fn router(topology: ShardTopology) -> ShardRouter {
+        ShardRouter::with_pools(topology, HashMap::new()).unwrap()
+    }
+
+    async fn file_pool(name: &str) -> (String, Arc<ConnectionPool>) {
+        let path = std::env::temp_dir().join(format!("caddy-shard-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let url = format!("sqlite://{}", path.display());
+        let pool = ConnectionPool::new(DatabaseConfig {
+            url: url.clone(),
+            min_connections: 1,
+            max_connections: 4,
+            enable_wal: false,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        sqlx::query("CREATE TABLE entities (id INTEGER PRIMARY KEY, tenant_id TEXT NOT NULL, name TEXT)")
+            .execute(pool.inner())
+            .await
+            .unwrap();
+        (url, Arc::new(pool))
+    }
+
+    #[test]
+    fn test_topology_validation() {
+        assert!(topology(2).validate().is_ok());
+        assert!(topology(2).pin("acme", 7).validate().is_err());
+
+        let mut duplicate = topology(2);
+        duplicate.shards[1].id = 0;
+        assert!(duplicate.validate().is_err());
+
+        let mut drained = topology(1);
+        drained.shards[0].state = ShardState::Draining;
+        assert!(drained.validate().is_err());
+
+        let json = topology(2).pin("acme", 1).to_json().unwrap();
+        assert_eq!(ShardTopology::from_json(&json).unwrap(), topology(2).pin("acme", 1));
+    }
+
+    #[test]
+    fn test_tenants_stay_where_placed() {
+        let router = router(topology(4).pin("acme", 3));
+        assert_eq!(router.shard_for("acme").unwrap(), 3);
+        assert_eq!(router.route(&ShardKey::tenant("acme")).unwrap(), 3);
+        assert!(router.route(&ShardKey::Int(1)).is_err());
+
+        let first = router.shard_for("globex").unwrap();
+        assert_eq!(router.shard_for("globex").unwrap(), first);
+
+        // Placement is sticky across topology changes until a move
+        {
+            let mut state = router.state.write();
+            state.topology.shards.retain(|s| s.id != first);
+            state.topology.shards.push(ShardNode::new(first, "sqlite://moved.db").with_state(ShardState::Draining));
+            state.ring = state.topology.ring();
+        }
+        assert_eq!(router.shard_for("globex").unwrap(), first);
+        assert_ne!(router.state.read().target("globex").unwrap(), first);
+    }
+
+    #[test]
+    fn test_weights_share_tenants() {
+        let mut weighted = topology(2);
+        weighted.shards[1].weight = 3;
+        let router = router(weighted);
+        let on_heavy = (0..4000)
+            .filter(|i| router.shard_for(&format!("tenant-{}", i)).unwrap() == 1)
+            .count();
+        assert!((2700..3300).contains(&on_heavy), "{}", on_heavy);
+    }
+
+    #[test]
+    fn test_rebalance_plan_moves_only_claimed_tenants() {
+        let router = router(topology(3));
+        let tenants: Vec<String> = (0..3000).map(|i| format!("tenant-{}", i)).collect();
+        for tenant in &tenants {
+            router.shard_for(tenant).unwrap();
+        }
+        assert!(router.plan_rebalance().unwrap().is_empty());
+
+        // A fourth shard claims about a quarter of the tenants, all of them
+        // arriving at the new shard
+        {
+            let mut state = router.state.write();
+            state.topology = topology(4);
+            state.ring = state.topology.ring();
+        }
+        let plan = router.plan_rebalance().unwrap();
+        assert!((600..900).contains(&plan.moves.len()), "{}", plan.moves.len());
+        assert!(plan.moves.iter().all(|m| m.to == 3));
+
+        // Draining a shard moves everything off it and nothing else
+        {
+            let mut state = router.state.write();
+            state.topology = topology(3);
+            state.topology.shards[0].state = ShardState::Draining;
+            state.ring = state.topology.ring();
+        }
+        let on_zero = router.directory().values().filter(|&&s| s == 0).count();
+        let plan = router.plan_rebalance().unwrap();
+        assert_eq!(plan.moves.len(), on_zero);
+        assert_eq!(plan.shard_changes()[&0], (on_zero, 0));
+    }
+
+    #[test]
+    fn test_writes_refused_during_move() {
+        let router = router(topology(1));
+        let lease = router.lease("acme");
+        let exclusive = lease.try_write_owned().unwrap();
+        assert!(router.writer("acme").is_err());
+        assert_eq!(router.stats().writes_refused, 1);
+        drop(exclusive);
+        assert!(router.lease("acme").try_read_owned().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_move_tenant_and_scatter_gather() {
+        let (url0, pool0) = file_pool("a").await;
+        let (url1, pool1) = file_pool("b").await;
+        let topology = ShardTopology::new(vec![ShardNode::new(0, url0), ShardNode::new(1, url1)]).pin("acme", 0);
+        let pools = HashMap::from([(0, pool0.clone()), (1, pool1.clone())]);
+        let router = ShardRouter::with_pools(topology, pools).unwrap();
+
+        {
+            let writer = router.writer("acme").unwrap();
+            assert_eq!(writer.shard(), 0);
+            for i in 0..3 {
+                sqlx::query("INSERT INTO entities (tenant_id, name) VALUES ('acme', ?)")
+                    .bind(format!("e{}", i))
+                    .execute(writer.inner())
+                    .await
+                    .unwrap();
+            }
+            sqlx::query("INSERT INTO entities (tenant_id, name) VALUES ('other', 'x')")
+                .execute(writer.inner())
+                .await
+                .unwrap();
+        }
+
+        let counts: ScatterGather<(i64,)> = router
+            .scatter_gather("SELECT COUNT(*) FROM entities", &ScatterOptions::default())
+            .await
+            .unwrap();
+        assert!(counts.is_complete());
+        assert_eq!(counts.rows().map(|(n,)| n).collect::<Vec<_>>(), vec![&4, &0]);
+
+        let tables = [TenantTable::new("entities", "tenant_id")];
+        let report = router
+            .move_tenant(
+                &TenantMove {
+                    tenant_id: "acme".into(),
+                    from: 0,
+                    to: 1,
+                },
+                &tables,
+            )
+            .await
+            .unwrap();
+        assert_eq!(report.rows_copied, 3);
+        assert_eq!(router.shard_for("acme").unwrap(), 1);
+
+        let names: ScatterGather<(String,)> = router
+            .scatter_gather("SELECT name FROM entities WHERE tenant_id = 'acme' ORDER BY name", &ScatterOptions::default())
+            .await
+            .unwrap();
+        assert!(names.results[0].rows.is_empty());
+        assert_eq!(names.results[1].rows.len(), 3);
+
+        // Bad identifiers never reach SQL
+        let bad = [TenantTable::new("entities; DROP TABLE entities", "tenant_id")];
+        let back = TenantMove {
+            tenant_id: "acme".into(),
+            from: 1,
+            to: 0,
+        };
+        assert!(router.move_tenant(&back, &bad).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scatter_gather_partial() {
+        let (url0, pool0) = file_pool("c").await;
+        let topology = ShardTopology::new(vec![
+            ShardNode::new(0, url0),
+            ShardNode::new(1, "sqlite://offline.db").with_state(ShardState::Offline),
+        ]);
+        let router = ShardRouter::with_pools(topology, HashMap::from([(0, pool0)])).unwrap();
+
+        let strict = router
+            .scatter_gather::<(i64,)>("SELECT COUNT(*) FROM entities", &ScatterOptions::default())
+            .await;
+        assert!(strict.is_err());
+
+        let options = ScatterOptions {
+            allow_partial: true,
+            ..Default::default()
+        };
+        let partial = router
+            .scatter_gather::<(i64,)>("SELECT COUNT(*) FROM entities", &options)
+            .await
+            .unwrap();
+        assert!(!partial.is_complete());
+        assert_eq!(partial.failures[0].0, 1);
+        assert_eq!(partial.into_rows(), vec![(0,)]);
+    }
+}
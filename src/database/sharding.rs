@@ -24,6 +24,9 @@ pub enum ShardKey {
 
     /// Composite key
     Composite(Vec<String>),
+
+    /// Tenant ID, routed by the tenant shard router
+    Tenant(String),
 }
 
 impl ShardKey {
@@ -34,6 +37,7 @@ impl ShardKey {
             ShardKey::Int(v) => v.hash(&mut hasher),
             ShardKey::String(v) => v.hash(&mut hasher),
             ShardKey::Uuid(v) => v.hash(&mut hasher),
+            ShardKey::Tenant(v) => v.hash(&mut hasher),
             ShardKey::Composite(parts) => {
                 for part in parts {
                     part.hash(&mut hasher);
@@ -43,6 +47,19 @@ impl ShardKey {
         hasher.finish()
     }
 
+    /// Key for a tenant
+    pub fn tenant(tenant_id: impl Into<String>) -> Self {
+        ShardKey::Tenant(tenant_id.into())
+    }
+
+    /// Tenant ID, for tenant keys
+    pub fn tenant_id(&self) -> Option<&str> {
+        match self {
+            ShardKey::Tenant(id) => Some(id),
+            _ => None,
+        }
+    }
+
     /// Get a numeric representation for range-based sharding
     pub fn as_number(&self) -> Option<i64> {
         match self {
@@ -273,8 +290,7 @@ impl ShardManager {
     /// Route using directory-based sharding
     fn route_directory(&self, key: &ShardKey) -> Result<usize> {
         let key_str = match key {
-            ShardKey::String(s) => s.clone(),
-            ShardKey::Uuid(s) => s.clone(),
+            ShardKey::String(s) | ShardKey::Uuid(s) | ShardKey::Tenant(s) => s.clone(),
             _ => return Err(DatabaseError::Sharding("Directory sharding requires string keys".to_string())),
         };
 
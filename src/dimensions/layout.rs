@@ -0,0 +1,653 @@
+//! Annotation layout
+//!
+//! Arranges leader labels within a view so that labels don't overlap each
+//! other, cover other leaders' arrow points, or sit across other leaders.
+//!
+//! Layout is view dependent: clearances and leader lengths are given in
+//! paper units and scaled into the view, so the same annotations spread out
+//! further in a small-scale view than in a detail view. Keep one
+//! [`AnnotationLayout`] per view.
+//!
+//! Placements are stable across regenerations. A label that was placed
+//! before keeps its offset from its arrow point as long as that spot is
+//! still clear, so only labels caught in a new conflict move. Pinned labels
+//! never move; the others are arranged around them.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::leader::{MLeaderAttachmentSide, MLeaderContent, MLeaderStyle, MultiLeader};
+use super::linear::Point3D;
+use super::text::TextStyle;
+
+/// View the annotations are laid out in
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LayoutView {
+    /// Lower-left corner of the visible model area
+    pub min: Point3D,
+    /// Upper-right corner of the visible model area
+    pub max: Point3D,
+    /// Paper units per model unit
+    pub scale: f64,
+}
+
+impl LayoutView {
+    /// Create a view over a model area at a plot scale
+    pub fn new(min: Point3D, max: Point3D, scale: f64) -> Self {
+        LayoutView { min, max, scale }
+    }
+
+    /// Convert a paper distance into model units in this view
+    pub fn to_model(&self, paper: f64) -> f64 {
+        if self.scale > 0.0 {
+            paper / self.scale
+        } else {
+            paper
+        }
+    }
+}
+
+/// Layout tuning, in paper units
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayoutOptions {
+    /// Minimum gap kept around labels
+    pub clearance: f64,
+    /// Distance from an arrow point to its label at the first ring
+    pub leader_length: f64,
+    /// Number of rings of candidate positions around each arrow point
+    pub rings: usize,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        LayoutOptions {
+            clearance: 1.0,
+            leader_length: 10.0,
+            rings: 6,
+        }
+    }
+}
+
+/// Label to be placed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelRequest {
+    /// Annotation identifier
+    pub id: Uuid,
+    /// Point the leader arrow touches
+    pub anchor: Point3D,
+    /// Label width in model units
+    pub width: f64,
+    /// Label height in model units
+    pub height: f64,
+}
+
+impl LabelRequest {
+    /// Create a label request
+    pub fn new(id: Uuid, anchor: Point3D, width: f64, height: f64) -> Self {
+        LabelRequest { id, anchor, width, height }
+    }
+
+    /// Label for a multi-leader: its first arrow point and content extents
+    pub fn from_multileader(mleader: &MultiLeader, style: &MLeaderStyle) -> Option<Self> {
+        let anchor = *mleader.leader_lines.first()?.first()?;
+        let (width, height) = match &mleader.content {
+            MLeaderContent::MText(mtext) => {
+                let mut text_style = TextStyle::new(style.text_style_name.clone());
+                text_style.height = mtext.height;
+                let (min, max) = mtext.bounding_box(&text_style);
+                (max.x - min.x, max.y - min.y)
+            }
+            MLeaderContent::Block { scale, .. } => {
+                let size = style.text_height * 2.0 * scale;
+                (size, size)
+            }
+            MLeaderContent::None => (0.0, 0.0),
+        };
+        Some(LabelRequest::new(mleader.id, anchor, width, height))
+    }
+}
+
+/// Where a label ended up
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LabelPlacement {
+    /// Annotation identifier
+    pub id: Uuid,
+    /// Point the leader arrow touches
+    pub anchor: Point3D,
+    /// Lower-left corner of the label
+    pub position: Point3D,
+    /// Point on the label the leader lands on
+    pub landing: Point3D,
+    /// Side of the label the leader attaches to
+    pub side: MLeaderAttachmentSide,
+    /// Whether the placement is a manual pin
+    pub pinned: bool,
+    /// Whether no clear spot was found and the label still conflicts
+    pub conflicted: bool,
+}
+
+/// Result of arranging a view
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LayoutReport {
+    /// Placements, in request order
+    pub placements: Vec<LabelPlacement>,
+    /// Labels that kept their previous placement
+    pub kept: usize,
+    /// Labels that were moved or placed for the first time
+    pub moved: usize,
+    /// Labels left with a conflict
+    pub conflicts: usize,
+}
+
+/// Leader label arrangement for one view
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnnotationLayout {
+    /// Layout tuning
+    pub options: LayoutOptions,
+    /// Label offsets from their arrow points, from the last arrangement
+    offsets: HashMap<Uuid, (f64, f64)>,
+    /// Manually pinned label positions (lower-left corners)
+    pins: HashMap<Uuid, Point3D>,
+}
+
+/// Axis-aligned label or obstacle box
+#[derive(Debug, Clone, Copy)]
+struct Rect {
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+}
+
+impl Rect {
+    fn at(position: Point3D, width: f64, height: f64) -> Self {
+        Rect {
+            min_x: position.x,
+            min_y: position.y,
+            max_x: position.x + width,
+            max_y: position.y + height,
+        }
+    }
+
+    fn inflate(&self, by: f64) -> Self {
+        Rect {
+            min_x: self.min_x - by,
+            min_y: self.min_y - by,
+            max_x: self.max_x + by,
+            max_y: self.max_y + by,
+        }
+    }
+
+    fn overlaps(&self, other: &Rect) -> bool {
+        self.min_x < other.max_x && other.min_x < self.max_x && self.min_y < other.max_y && other.min_y < self.max_y
+    }
+
+    fn contains(&self, p: &Point3D) -> bool {
+        p.x > self.min_x && p.x < self.max_x && p.y > self.min_y && p.y < self.max_y
+    }
+
+    fn within(&self, view: &LayoutView) -> bool {
+        self.min_x >= view.min.x && self.min_y >= view.min.y && self.max_x <= view.max.x && self.max_y <= view.max.y
+    }
+
+    /// Whether a segment passes through the box
+    fn crossed_by(&self, a: &Point3D, b: &Point3D) -> bool {
+        // Liang-Barsky clipping
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let mut t0: f64 = 0.0;
+        let mut t1: f64 = 1.0;
+        for (p, q) in [
+            (-dx, a.x - self.min_x),
+            (dx, self.max_x - a.x),
+            (-dy, a.y - self.min_y),
+            (dy, self.max_y - a.y),
+        ] {
+            if p.abs() < 1e-12 {
+                if q < 0.0 {
+                    return false;
+                }
+            } else {
+                let t = q / p;
+                if p < 0.0 {
+                    t0 = t0.max(t);
+                } else {
+                    t1 = t1.min(t);
+                }
+            }
+        }
+        t1 - t0 > 1e-9
+    }
+}
+
+/// Whether two segments cross at a point interior to both
+fn segments_cross(a: &Point3D, b: &Point3D, c: &Point3D, d: &Point3D) -> bool {
+    let orient = |p: &Point3D, q: &Point3D, r: &Point3D| (q.x - p.x) * (r.y - p.y) - (q.y - p.y) * (r.x - p.x);
+    let (d1, d2) = (orient(a, b, c), orient(a, b, d));
+    let (d3, d4) = (orient(c, d, a), orient(c, d, b));
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+/// Label box placed so far, with its leader
+struct Placed {
+    rect: Rect,
+    anchor: Point3D,
+    landing: Point3D,
+}
+
+/// Extra passes over labels left in conflict after the first placement
+const REPAIR_PASSES: usize = 4;
+
+/// Candidate directions from the arrow point, in order of preference
+const DIRECTIONS: [(f64, f64); 8] = [
+    (1.0, 1.0),
+    (-1.0, 1.0),
+    (1.0, -1.0),
+    (-1.0, -1.0),
+    (1.0, 0.0),
+    (-1.0, 0.0),
+    (0.0, 1.0),
+    (0.0, -1.0),
+];
+
+impl AnnotationLayout {
+    /// Create an empty layout with default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set layout options
+    pub fn with_options(mut self, options: LayoutOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Pin a label at a position (its lower-left corner), overriding layout
+    pub fn pin(&mut self, id: Uuid, position: Point3D) {
+        self.pins.insert(id, position);
+    }
+
+    /// Release a pinned label back to automatic layout
+    pub fn unpin(&mut self, id: Uuid) -> bool {
+        self.pins.remove(&id).is_some()
+    }
+
+    /// Whether a label is pinned
+    pub fn is_pinned(&self, id: Uuid) -> bool {
+        self.pins.contains_key(&id)
+    }
+
+    /// Forget the previous placement of a label so it is arranged afresh
+    pub fn reset(&mut self, id: Uuid) {
+        self.offsets.remove(&id);
+    }
+
+    /// Arrange labels in a view
+    ///
+    /// Pinned labels are placed first, then labels that were placed before,
+    /// then new ones; within each group labels go in request order, so the
+    /// same input always gives the same layout. Labels left in conflict are
+    /// then moved again against the full layout, along with any label that
+    /// was placed this time round and is in their way. Labels whose arrow
+    /// point is gone are forgotten.
+    pub fn arrange(&mut self, view: &LayoutView, requests: &[LabelRequest]) -> LayoutReport {
+        let clearance = view.to_model(self.options.clearance);
+        let mut order: Vec<usize> = (0..requests.len()).collect();
+        order.sort_by_key(|&i| {
+            let id = requests[i].id;
+            if self.pins.contains_key(&id) {
+                0
+            } else if self.offsets.contains_key(&id) {
+                1
+            } else {
+                2
+            }
+        });
+
+        let mut positions: Vec<Option<Point3D>> = vec![None; requests.len()];
+        let mut movable = vec![false; requests.len()];
+        let mut report = LayoutReport::default();
+
+        for &i in &order {
+            let request = &requests[i];
+            let placed = Self::placed(requests, &positions, i);
+            let previous = self
+                .offsets
+                .get(&request.id)
+                .map(|&(dx, dy)| Point3D::new(request.anchor.x + dx, request.anchor.y + dy, request.anchor.z));
+
+            positions[i] = Some(if let Some(position) = self.pins.get(&request.id) {
+                *position
+            } else if let Some(position) =
+                previous.filter(|p| self.conflicts(view, request, *p, &placed, requests, clearance) == 0)
+            {
+                report.kept += 1;
+                position
+            } else {
+                report.moved += 1;
+                movable[i] = true;
+                self.best_position(view, request, &placed, requests, clearance).0
+            });
+        }
+
+        // Greedy placement can box a late label in; give labels placed this
+        // round a few more tries against everything else
+        for _ in 0..REPAIR_PASSES {
+            let mut changed = false;
+            for &i in order.iter().filter(|&&i| movable[i]) {
+                let request = &requests[i];
+                let others = Self::placed(requests, &positions, i);
+                let current = positions[i].unwrap_or(request.anchor);
+                let now = self.conflicts(view, request, current, &others, requests, clearance);
+                if now == 0 {
+                    continue;
+                }
+                let (position, conflicts) = self.best_position(view, request, &others, requests, clearance);
+                if conflicts < now {
+                    positions[i] = Some(position);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        for (i, request) in requests.iter().enumerate() {
+            let position = positions[i].unwrap_or(request.anchor);
+            let others = Self::placed(requests, &positions, i);
+            let conflicts = self.conflicts(view, request, position, &others, requests, clearance);
+            let (landing, side) = Self::landing(request, position);
+            if conflicts > 0 {
+                report.conflicts += 1;
+            }
+            self.offsets.insert(request.id, (position.x - request.anchor.x, position.y - request.anchor.y));
+            report.placements.push(LabelPlacement {
+                id: request.id,
+                anchor: request.anchor,
+                position,
+                landing,
+                side,
+                pinned: self.pins.contains_key(&request.id),
+                conflicted: conflicts > 0,
+            });
+        }
+
+        let live: Vec<Uuid> = requests.iter().map(|r| r.id).collect();
+        self.offsets.retain(|id, _| live.contains(id));
+
+        report
+    }
+
+    /// Arrange multi-leaders in a view and move their content and landings
+    pub fn arrange_multileaders(
+        &mut self,
+        view: &LayoutView,
+        mleaders: &mut [MultiLeader],
+        style: &MLeaderStyle,
+    ) -> LayoutReport {
+        let requests: Vec<LabelRequest> = mleaders
+            .iter()
+            .filter_map(|m| LabelRequest::from_multileader(m, style))
+            .collect();
+        let report = self.arrange(view, &requests);
+
+        let landing_length = view.to_model(style.landing_distance + style.landing_gap);
+        for placement in &report.placements {
+            let Some(mleader) = mleaders.iter_mut().find(|m| m.id == placement.id) else {
+                continue;
+            };
+            let offset = match placement.side {
+                MLeaderAttachmentSide::Left => (-landing_length, 0.0),
+                MLeaderAttachmentSide::Right => (landing_length, 0.0),
+                MLeaderAttachmentSide::Top => (0.0, landing_length),
+                MLeaderAttachmentSide::Bottom => (0.0, -landing_length),
+            };
+            let elbow = Point3D::new(placement.landing.x + offset.0, placement.landing.y + offset.1, placement.landing.z);
+
+            mleader.content_position = placement.position;
+            mleader.attachment_side = placement.side;
+            if let MLeaderContent::MText(mtext) = &mut mleader.content {
+                mtext.position = placement.position;
+            }
+            for line in &mut mleader.leader_lines {
+                match line.len() {
+                    0 => {}
+                    1 => line.push(elbow),
+                    n => line[n - 1] = elbow,
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Labels placed so far, other than `skip`
+    fn placed(requests: &[LabelRequest], positions: &[Option<Point3D>], skip: usize) -> Vec<Placed> {
+        requests
+            .iter()
+            .zip(positions)
+            .enumerate()
+            .filter(|&(i, _)| i != skip)
+            .filter_map(|(_, (request, position))| {
+                let position = (*position)?;
+                Some(Placed {
+                    rect: Rect::at(position, request.width, request.height),
+                    anchor: request.anchor,
+                    landing: Self::landing(request, position).0,
+                })
+            })
+            .collect()
+    }
+
+    /// Lowest-cost candidate position around the arrow point
+    fn best_position(
+        &self,
+        view: &LayoutView,
+        request: &LabelRequest,
+        placed: &[Placed],
+        requests: &[LabelRequest],
+        clearance: f64,
+    ) -> (Point3D, usize) {
+        let step = view.to_model(self.options.leader_length);
+        let mut best: Option<(Point3D, usize)> = None;
+
+        for ring in 1..=self.options.rings.max(1) {
+            let distance = step * ring as f64;
+            for (dx, dy) in DIRECTIONS {
+                let length = (dx * dx + dy * dy).sqrt();
+                let (ux, uy) = (dx / length, dy / length);
+                // Put the label's near edge, not its corner, at the distance
+                let x = request.anchor.x + ux * distance
+                    - if ux < -0.5 {
+                        request.width
+                    } else if ux.abs() <= 0.5 {
+                        request.width / 2.0
+                    } else {
+                        0.0
+                    };
+                let y = request.anchor.y + uy * distance
+                    - if uy < -0.5 {
+                        request.height
+                    } else if uy.abs() <= 0.5 {
+                        request.height / 2.0
+                    } else {
+                        0.0
+                    };
+                let position = Point3D::new(x, y, request.anchor.z);
+                let conflicts = self.conflicts(view, request, position, placed, requests, clearance);
+                if conflicts == 0 {
+                    return (position, 0);
+                }
+                if best.is_none_or(|(_, c)| conflicts < c) {
+                    best = Some((position, conflicts));
+                }
+            }
+        }
+
+        best.unwrap_or((request.anchor, 0))
+    }
+
+    /// Number of conflicts a label at `position` would have
+    fn conflicts(
+        &self,
+        view: &LayoutView,
+        request: &LabelRequest,
+        position: Point3D,
+        placed: &[Placed],
+        requests: &[LabelRequest],
+        clearance: f64,
+    ) -> usize {
+        let rect = Rect::at(position, request.width, request.height);
+        let padded = rect.inflate(clearance);
+        let (landing, _) = Self::landing(request, position);
+        let mut count = usize::from(!rect.within(view));
+
+        for other in placed {
+            if padded.overlaps(&other.rect) {
+                count += 1;
+            }
+            if other.rect.crossed_by(&request.anchor, &landing) || rect.crossed_by(&other.anchor, &other.landing) {
+                count += 1;
+            }
+            if segments_cross(&request.anchor, &landing, &other.anchor, &other.landing) {
+                count += 1;
+            }
+        }
+        for other in requests {
+            if other.id != request.id
+                && (padded.contains(&other.anchor)
+                    || distance_to_segment(&other.anchor, &request.anchor, &landing) < clearance)
+            {
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    /// Landing point on the label edge facing the arrow point
+    ///
+    /// Labels clear of their arrow point to one side take a horizontal
+    /// landing on the near side, which reads best; labels straight above or
+    /// below land on their bottom or top edge.
+    fn landing(request: &LabelRequest, position: Point3D) -> (Point3D, MLeaderAttachmentSide) {
+        let center_x = position.x + request.width / 2.0;
+        let center_y = position.y + request.height / 2.0;
+        let z = position.z;
+
+        if position.x >= request.anchor.x {
+            (Point3D::new(position.x, center_y, z), MLeaderAttachmentSide::Left)
+        } else if position.x + request.width <= request.anchor.x {
+            (Point3D::new(position.x + request.width, center_y, z), MLeaderAttachmentSide::Right)
+        } else if center_y >= request.anchor.y {
+            (Point3D::new(center_x, position.y, z), MLeaderAttachmentSide::Bottom)
+        } else {
+            (Point3D::new(center_x, position.y + request.height, z), MLeaderAttachmentSide::Top)
+        }
+    }
+}
+
+/// Distance from a point to a segment, in plan
+fn distance_to_segment(p: &Point3D, a: &Point3D, b: &Point3D) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq > 0.0 {
+        (((p.x - a.x) * dx + (p.y - a.y) * dy) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    ((a.x + dx * t - p.x).powi(2) + (a.y + dy * t - p.y).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dimensions::text::MText;
+
+    fn view() -> LayoutView {
+        LayoutView::new(Point3D::new(-200.0, -200.0, 0.0), Point3D::new(200.0, 200.0, 0.0), 1.0)
+    }
+
+    fn label(x: f64, y: f64) -> LabelRequest {
+        LabelRequest::new(Uuid::new_v4(), Point3D::new(x, y, 0.0), 20.0, 5.0)
+    }
+
+    #[test]
+    fn test_dense_labels_do_not_overlap() {
+        let requests: Vec<_> = (0..6).map(|i| label(i as f64 * 3.0, 0.0)).collect();
+        let mut layout = AnnotationLayout::new();
+        let report = layout.arrange(&view(), &requests);
+
+        assert_eq!(report.placements.len(), 6);
+        assert_eq!(report.conflicts, 0);
+        for (i, a) in report.placements.iter().enumerate() {
+            for b in &report.placements[i + 1..] {
+                let ra = Rect::at(a.position, 20.0, 5.0);
+                let rb = Rect::at(b.position, 20.0, 5.0);
+                assert!(!ra.overlaps(&rb));
+            }
+        }
+    }
+
+    #[test]
+    fn test_layout_is_stable_and_pins_override() {
+        let mut requests = vec![label(0.0, 0.0), label(50.0, 0.0)];
+        let mut layout = AnnotationLayout::new();
+        let first = layout.arrange(&view(), &requests);
+        let again = layout.arrange(&view(), &requests);
+        assert_eq!(first.placements, again.placements);
+        assert_eq!(again.kept, 2);
+
+        // A new label next to the first leaves the existing ones in place
+        requests.push(label(4.0, 0.0));
+        let third = layout.arrange(&view(), &requests);
+        assert_eq!(third.placements[..2], first.placements[..2]);
+        assert_eq!(third.moved, 1);
+
+        // Pinned labels stay put and the others make way
+        let pinned_at = first.placements[0].position;
+        layout.pin(requests[1].id, pinned_at);
+        let pinned = layout.arrange(&view(), &requests);
+        assert!(pinned.placements[1].pinned);
+        assert_eq!(pinned.placements[1].position, pinned_at);
+        assert_ne!(pinned.placements[0].position, pinned_at);
+        assert_eq!(pinned.conflicts, 0);
+
+        assert!(layout.unpin(requests[1].id));
+        assert!(!layout.is_pinned(requests[1].id));
+    }
+
+    #[test]
+    fn test_view_scale_spreads_labels() {
+        let requests = vec![label(0.0, 0.0)];
+        let detail = AnnotationLayout::new().arrange(&view(), &requests);
+        let mut overview = view();
+        overview.scale = 0.5;
+        let overview = AnnotationLayout::new().arrange(&overview, &requests);
+
+        let reach = |report: &LayoutReport| report.placements[0].landing.distance_to(&report.placements[0].anchor);
+        assert!(reach(&overview) > reach(&detail) * 1.5);
+    }
+
+    #[test]
+    fn test_arrange_multileaders() {
+        let style = MLeaderStyle::standard();
+        let mut mleaders: Vec<_> = (0..3)
+            .map(|i| {
+                let anchor = Point3D::new(i as f64 * 2.0, 0.0, 0.0);
+                let mtext = MText::new("DETAIL NOTE", anchor, 2.5, "Standard");
+                MultiLeader::new(vec![anchor, anchor], MLeaderContent::MText(mtext), anchor, "Standard")
+            })
+            .collect();
+
+        let report = AnnotationLayout::new().arrange_multileaders(&view(), &mut mleaders, &style);
+        assert_eq!(report.conflicts, 0);
+        for (mleader, placement) in mleaders.iter().zip(&report.placements) {
+            assert_eq!(mleader.content_position, placement.position);
+            assert_eq!(mleader.attachment_side, placement.side);
+            assert_eq!(mleader.get_landing_lines(&style).len(), 1);
+        }
+    }
+}
@@ -11,6 +11,7 @@
 //! - **Radial Dimensions**: Radius, diameter, jogged radius
 //! - **Text Annotations**: Single-line and multi-line formatted text
 //! - **Leaders**: Traditional leaders and modern multi-leaders
+//! - **Annotation Layout**: Overlap-free, stable leader label arrangement per view, with manual pins
 //! - **Associativity**: Dimensions automatically update with geometry changes
 //!
 //! # Example
@@ -37,6 +38,7 @@ pub mod angular;
 pub mod radial;
 pub mod text;
 pub mod leader;
+pub mod layout;
 
 // Re-export commonly used types
 pub use style::{
@@ -103,6 +105,15 @@ pub use leader::{
     DatumReference,
 };
 
+pub use layout::{
+    AnnotationLayout,
+    LayoutView,
+    LayoutOptions,
+    LabelRequest,
+    LabelPlacement,
+    LayoutReport,
+};
+
 #[cfg(test)]
 mod tests {
     use super::*;
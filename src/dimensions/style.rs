@@ -3,6 +3,8 @@
 //! This module provides comprehensive dimension styling capabilities including
 //! text formatting, arrow styles, extension lines, and standard templates.
 
+use crate::io::readout::{AngleFormat, ReadoutFormat, StationFormat};
+use crate::io::regional::DimensionStandard;
 use serde::{Deserialize, Serialize};
 
 /// Arrow type for dimension lines
@@ -69,6 +71,8 @@ pub enum UnitFormat {
     Fractional,
     /// Windows desktop units
     WindowsDesktop,
+    /// Civil stationing (`12+34.56`)
+    Station,
 }
 
 /// Angular unit format
@@ -203,6 +207,9 @@ pub struct DimensionStyle {
     pub angular_unit_format: AngularUnitFormat,
     /// Angular precision
     pub angular_precision: u8,
+    /// Distance between full stations for [`UnitFormat::Station`]
+    #[serde(default = "default_station_interval")]
+    pub station_interval: f64,
     /// Unit scale factor
     pub scale_factor: f64,
     /// Prefix for dimension text
@@ -285,6 +292,7 @@ impl DimensionStyle {
             precision: 2,
             angular_unit_format: AngularUnitFormat::DecimalDegrees,
             angular_precision: 0,
+            station_interval: default_station_interval(),
             scale_factor: 1.0,
             prefix: String::new(),
             suffix: String::new(),
@@ -368,6 +376,16 @@ impl DimensionStyle {
         style
     }
 
+    /// Style of a dimension standard, as chosen by a document's regional settings
+    pub fn for_standard(standard: DimensionStandard) -> Self {
+        match standard {
+            DimensionStandard::Iso => Self::iso(),
+            DimensionStandard::Ansi => Self::ansi(),
+            DimensionStandard::Din => Self::din(),
+            DimensionStandard::Jis => Self::jis(),
+        }
+    }
+
     /// Create architectural dimension style
     pub fn architectural() -> Self {
         let mut style = DimensionStyle::new("Architectural");
@@ -409,6 +427,11 @@ impl DimensionStyle {
             UnitFormat::WindowsDesktop => {
                 self.format_decimal(scaled_value)
             }
+            UnitFormat::Station => StationFormat {
+                interval: self.station_interval,
+                precision: self.precision as usize,
+            }
+            .format(scaled_value),
         };
 
         let mut result = format!("{}{}{}", self.prefix, formatted, self.suffix);
@@ -442,7 +465,7 @@ impl DimensionStyle {
                 format!("{}°", self.format_decimal_with_precision(degrees, self.angular_precision))
             }
             AngularUnitFormat::DegMinSec => {
-                self.format_deg_min_sec(degrees)
+                self.angle_readout(AngleFormat::DegMinSec).format_angle(radians)
             }
             AngularUnitFormat::Gradians => {
                 let gradians = degrees * 10.0 / 9.0;
@@ -452,7 +475,7 @@ impl DimensionStyle {
                 format!("{}r", self.format_decimal_with_precision(radians, self.angular_precision))
             }
             AngularUnitFormat::Surveyors => {
-                self.format_surveyors(degrees)
+                self.angle_readout(AngleFormat::Bearing).format_direction(radians)
             }
         }
     }
@@ -569,18 +592,12 @@ impl DimensionStyle {
         a
     }
 
-    fn format_deg_min_sec(&self, degrees: f64) -> String {
-        let deg = degrees.floor();
-        let min_decimal = (degrees - deg) * 60.0;
-        let min = min_decimal.floor();
-        let sec = (min_decimal - min) * 60.0;
-        format!("{}°{}'{}\"", deg, min, sec.round())
-    }
-
-    fn format_surveyors(&self, degrees: f64) -> String {
-        // Surveyor's units: N/S direction, angle, E/W direction
-        // Simplified implementation
-        format!("N {}° E", self.format_decimal_with_precision(degrees, self.angular_precision))
+    fn angle_readout(&self, angle: AngleFormat) -> ReadoutFormat {
+        ReadoutFormat {
+            angle,
+            angle_precision: self.angular_precision as usize,
+            ..ReadoutFormat::default()
+        }
     }
 
     fn add_tolerance(&self, base: String, value: f64) -> String {
@@ -609,6 +626,10 @@ impl DimensionStyle {
     }
 }
 
+fn default_station_interval() -> f64 {
+    100.0
+}
+
 impl Default for DimensionStyle {
     fn default() -> Self {
         DimensionStyle::iso()
@@ -648,4 +669,33 @@ mod tests {
         let result = style.format_linear(12.5); // 1 foot 0.5 inches
         assert!(result.contains('\''));
     }
+
+    #[test]
+    fn test_style_for_standard() {
+        let standards = [
+            DimensionStandard::Iso,
+            DimensionStandard::Ansi,
+            DimensionStandard::Din,
+            DimensionStandard::Jis,
+        ];
+        for standard in standards {
+            assert_eq!(DimensionStyle::for_standard(standard).name, standard.style_name());
+        }
+    }
+
+    #[test]
+    fn test_surveyor_and_station_formats() {
+        let mut style = DimensionStyle::ansi();
+        style.angular_unit_format = AngularUnitFormat::Surveyors;
+        style.angular_precision = 2;
+        assert_eq!(style.format_angular(135.0_f64.to_radians()), "N45°00'W");
+
+        style.angular_unit_format = AngularUnitFormat::DegMinSec;
+        style.angular_precision = 4;
+        assert_eq!(style.format_angular(30.25_f64.to_radians()), "30°15'00\"");
+
+        style.unit_format = UnitFormat::Station;
+        style.precision = 2;
+        assert_eq!(style.format_linear(1234.5), "12+34.50");
+    }
 }
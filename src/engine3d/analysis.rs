@@ -5,7 +5,7 @@
 
 use super::mesh::{HalfEdgeMesh, VertexHandle, FaceHandle, MeshError};
 use crate::core::{Point3, Vector3, EPSILON};
-use nalgebra::{Matrix3, Vector3 as NVector3};
+use crate::geometry::mass::SolidProperties;
 use serde::{Deserialize, Serialize};
 use std::f64::consts::PI;
 
@@ -16,12 +16,18 @@ pub struct MassProperties {
     pub volume: f64,
     /// Surface area
     pub surface_area: f64,
+    /// Volume times density
+    pub mass: f64,
+    /// Mass per unit volume
+    pub density: f64,
     /// Center of mass
     pub center_of_mass: Point3,
-    /// Inertia tensor (relative to center of mass)
+    /// Inertia tensor (relative to center of mass, in mass units)
     pub inertia_tensor: [[f64; 3]; 3],
-    /// Principal moments of inertia
+    /// Principal moments of inertia, smallest first
     pub principal_moments: [f64; 3],
+    /// Unit principal axes, in the order of the moments
+    pub principal_axes: [Vector3; 3],
     /// Bounding box min point
     pub bbox_min: Point3,
     /// Bounding box max point
@@ -33,87 +39,42 @@ pub struct GeometryAnalyzer;
 
 impl GeometryAnalyzer {
     /// Compute mass properties for a closed mesh
-    /// Assumes the mesh is a closed, manifold solid with consistent outward normals
+    /// Assumes the mesh is a closed, manifold solid with consistently wound faces
     pub fn compute_mass_properties(mesh: &HalfEdgeMesh, density: f64) -> Result<MassProperties, AnalysisError> {
         if !mesh.is_manifold() {
             return Err(AnalysisError::NonManifoldMesh);
         }
 
-        // Compute volume and center of mass using divergence theorem
-        let (volume, centroid) = Self::compute_volume_and_centroid(mesh)?;
-
-        if volume < EPSILON {
-            return Err(AnalysisError::ZeroVolume);
-        }
-
-        // Compute surface area
-        let surface_area = Self::compute_surface_area(mesh)?;
-
-        // Compute inertia tensor
-        let inertia_tensor = Self::compute_inertia_tensor(mesh, &centroid, density)?;
-
-        // Compute principal moments (eigenvalues of inertia tensor)
-        let principal_moments = Self::compute_principal_moments(&inertia_tensor);
-
-        // Compute bounding box
-        let (bbox_min, bbox_max) = Self::compute_bounding_box(mesh)?;
-
-        Ok(MassProperties {
-            volume: volume.abs(),
-            surface_area,
-            center_of_mass: centroid,
-            inertia_tensor,
-            principal_moments,
-            bbox_min,
-            bbox_max,
-        })
-    }
-
-    /// Compute volume and centroid using the divergence theorem
-    fn compute_volume_and_centroid(mesh: &HalfEdgeMesh) -> Result<(f64, Point3), AnalysisError> {
-        let mut volume = 0.0;
-        let mut centroid = Vector3::zeros();
-
+        let mut faces = Vec::new();
         for fh in mesh.face_handles() {
             let vertices = mesh.face_vertices(fh).map_err(|_| AnalysisError::InvalidMesh)?;
-
-            if vertices.len() < 3 {
-                continue;
-            }
-
-            // Get first vertex as reference
-            let v0 = mesh.get_vertex(vertices[0]).map_err(|_| AnalysisError::InvalidMesh)?;
-            let p0 = v0.position;
-
-            // Triangulate the face and sum contributions
-            for i in 1..vertices.len() - 1 {
-                let v1 = mesh.get_vertex(vertices[i]).map_err(|_| AnalysisError::InvalidMesh)?;
-                let v2 = mesh.get_vertex(vertices[i + 1]).map_err(|_| AnalysisError::InvalidMesh)?;
-
-                let p1 = v1.position;
-                let p2 = v2.position;
-
-                // Signed volume of tetrahedron formed by origin and triangle
-                let tet_volume = Self::tetrahedron_volume(&Point3::origin(), &p0, &p1, &p2);
-                volume += tet_volume;
-
-                // Contribution to centroid
-                let tet_centroid = (p0.coords + p1.coords + p2.coords) / 4.0;
-                centroid += tet_centroid * tet_volume;
-            }
+            let points = vertices
+                .iter()
+                .map(|&vh| mesh.get_vertex(vh).map(|v| v.position))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| AnalysisError::InvalidMesh)?;
+            faces.push(points);
         }
 
-        if volume.abs() < EPSILON {
-            return Ok((0.0, Point3::origin()));
-        }
-
-        centroid /= volume;
-
-        Ok((volume, Point3::from(centroid)))
+        // Volume integrals and inertia (Eberly), corrected for inward winding
+        let props = SolidProperties::from_faces(&faces, density).ok_or(AnalysisError::ZeroVolume)?;
+        let axes = props.principal_axes;
+        Ok(MassProperties {
+            volume: props.volume,
+            surface_area: props.surface_area,
+            mass: props.mass,
+            density,
+            center_of_mass: props.centroid,
+            inertia_tensor: [0, 1, 2].map(|i| [0, 1, 2].map(|j| props.inertia[(i, j)])),
+            principal_moments: props.principal_moments,
+            principal_axes: [0, 1, 2].map(|i| Vector3::new(axes[(0, i)], axes[(1, i)], axes[(2, i)])),
+            bbox_min: props.bounds.0,
+            bbox_max: props.bounds.1,
+        })
     }
 
     /// Compute signed volume of a tetrahedron
-    fn tetrahedron_volume(p0: &Point3, p1: &Point3, p2: &Point3, p3: &Point3) -> f64 {
+    pub fn tetrahedron_volume(p0: &Point3, p1: &Point3, p2: &Point3, p3: &Point3) -> f64 {
         let v1 = p1 - p0;
         let v2 = p2 - p0;
         let v3 = p3 - p0;
@@ -121,156 +82,13 @@ impl GeometryAnalyzer {
         v1.dot(&v2.cross(&v3)) / 6.0
     }
 
-    /// Compute surface area
-    fn compute_surface_area(mesh: &HalfEdgeMesh) -> Result<f64, AnalysisError> {
-        let mut area = 0.0;
-
-        for fh in mesh.face_handles() {
-            let vertices = mesh.face_vertices(fh).map_err(|_| AnalysisError::InvalidMesh)?;
-
-            if vertices.len() < 3 {
-                continue;
-            }
-
-            // Triangulate and sum areas
-            let v0 = mesh.get_vertex(vertices[0]).map_err(|_| AnalysisError::InvalidMesh)?;
-            let p0 = v0.position;
-
-            for i in 1..vertices.len() - 1 {
-                let v1 = mesh.get_vertex(vertices[i]).map_err(|_| AnalysisError::InvalidMesh)?;
-                let v2 = mesh.get_vertex(vertices[i + 1]).map_err(|_| AnalysisError::InvalidMesh)?;
-
-                let p1 = v1.position;
-                let p2 = v2.position;
-
-                area += Self::triangle_area(&p0, &p1, &p2);
-            }
-        }
-
-        Ok(area)
-    }
-
     /// Compute area of a triangle
-    fn triangle_area(p0: &Point3, p1: &Point3, p2: &Point3) -> f64 {
+    pub fn triangle_area(p0: &Point3, p1: &Point3, p2: &Point3) -> f64 {
         let v1 = p1 - p0;
         let v2 = p2 - p0;
         v1.cross(&v2).norm() / 2.0
     }
 
-    /// Compute inertia tensor
-    fn compute_inertia_tensor(
-        mesh: &HalfEdgeMesh,
-        centroid: &Point3,
-        density: f64,
-    ) -> Result<[[f64; 3]; 3], AnalysisError> {
-        let mut inertia = [[0.0f64; 3]; 3];
-
-        for fh in mesh.face_handles() {
-            let vertices = mesh.face_vertices(fh).map_err(|_| AnalysisError::InvalidMesh)?;
-
-            if vertices.len() < 3 {
-                continue;
-            }
-
-            let v0 = mesh.get_vertex(vertices[0]).map_err(|_| AnalysisError::InvalidMesh)?;
-            let p0 = v0.position - centroid;
-
-            for i in 1..vertices.len() - 1 {
-                let v1 = mesh.get_vertex(vertices[i]).map_err(|_| AnalysisError::InvalidMesh)?;
-                let v2 = mesh.get_vertex(vertices[i + 1]).map_err(|_| AnalysisError::InvalidMesh)?;
-
-                let p1 = v1.position - centroid;
-                let p2 = v2.position - centroid;
-
-                // Tetrahedron contribution
-                let tet_vol = Self::tetrahedron_volume(&Point3::origin(), &p0, &p1, &p2);
-
-                // Add to inertia tensor
-                for j in 0..3 {
-                    for k in 0..3 {
-                        let contrib = Self::tetrahedron_inertia_component(
-                            &Point3::origin(), &p0, &p1, &p2,
-                            j, k,
-                        );
-                        inertia[j][k] += contrib * density * tet_vol.abs();
-                    }
-                }
-            }
-        }
-
-        Ok(inertia)
-    }
-
-    /// Compute inertia tensor component for a tetrahedron
-    fn tetrahedron_inertia_component(
-        p0: &Point3,
-        p1: &Point3,
-        p2: &Point3,
-        p3: &Point3,
-        i: usize,
-        j: usize,
-    ) -> f64 {
-        // Simplified inertia calculation
-        let points = [p0, p1, p2, p3];
-        let mut sum = 0.0;
-
-        for p in &points {
-            let coords = [p.x, p.y, p.z];
-            if i == j {
-                // Diagonal terms
-                sum += coords.iter().enumerate()
-                    .filter(|(idx, _)| *idx != i)
-                    .map(|(_, &c)| c * c)
-                    .sum::<f64>();
-            } else {
-                // Off-diagonal terms
-                sum -= coords[i] * coords[j];
-            }
-        }
-
-        sum / 20.0 // Integration factor for tetrahedron
-    }
-
-    /// Compute principal moments of inertia (eigenvalues)
-    fn compute_principal_moments(inertia: &[[f64; 3]; 3]) -> [f64; 3] {
-        // Convert to nalgebra matrix for eigenvalue computation
-        let matrix = Matrix3::new(
-            inertia[0][0], inertia[0][1], inertia[0][2],
-            inertia[1][0], inertia[1][1], inertia[1][2],
-            inertia[2][0], inertia[2][1], inertia[2][2],
-        );
-
-        // Compute eigenvalues
-        if let Some(eigen) = matrix.symmetric_eigen() {
-            let eigenvalues = eigen.eigenvalues;
-            [eigenvalues[0], eigenvalues[1], eigenvalues[2]]
-        } else {
-            // Fallback if eigenvalue computation fails
-            [inertia[0][0], inertia[1][1], inertia[2][2]]
-        }
-    }
-
-    /// Compute bounding box
-    fn compute_bounding_box(mesh: &HalfEdgeMesh) -> Result<(Point3, Point3), AnalysisError> {
-        let mut min = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
-        let mut max = Point3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
-
-        for vh in mesh.vertex_handles() {
-            let vertex = mesh.get_vertex(vh).map_err(|_| AnalysisError::InvalidMesh)?;
-            let p = vertex.position;
-
-            min.x = min.x.min(p.x);
-            min.y = min.y.min(p.y);
-            min.z = min.z.min(p.z);
-
-            max.x = max.x.max(p.x);
-            max.y = max.y.max(p.y);
-            max.z = max.z.max(p.z);
-        }
-
-        Ok((min, max))
-    }
-
     /// Compute Gaussian curvature at a vertex
     pub fn compute_gaussian_curvature(mesh: &HalfEdgeMesh, vh: VertexHandle) -> Result<f64, AnalysisError> {
         let vertex = mesh.get_vertex(vh).map_err(|_| AnalysisError::InvalidMesh)?;
@@ -425,11 +243,15 @@ pub enum AnalysisError {
 
     #[error("Invalid mesh structure")]
     InvalidMesh,
+
+    #[error("Direction has zero length")]
+    ZeroDirection,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::engine3d::topology::ExtrudeOperation;
 
     #[test]
     fn test_triangle_area() {
@@ -451,4 +273,32 @@ mod tests {
         let volume = GeometryAnalyzer::tetrahedron_volume(&p0, &p1, &p2, &p3);
         assert!((volume - 1.0 / 6.0).abs() < EPSILON);
     }
+
+    #[test]
+    fn test_extruded_mass_properties() {
+        // 2 x 3 x 4 block with the profile wound clockwise
+        let profile = vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.0, 3.0, 0.0),
+            Point3::new(2.0, 3.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+        ];
+        let mesh = ExtrudeOperation {
+            direction: Vector3::new(0.0, 0.0, 4.0),
+            ..Default::default()
+        }
+        .extrude_profile(&profile)
+        .unwrap();
+
+        let props = GeometryAnalyzer::compute_mass_properties(&mesh, 7.85).unwrap();
+        assert!((props.volume - 24.0).abs() < 1e-9);
+        assert!((props.surface_area - 52.0).abs() < 1e-9);
+        assert!((props.mass - 24.0 * 7.85).abs() < 1e-9);
+        assert!((props.center_of_mass - Point3::new(1.0, 1.5, 2.0)).norm() < 1e-9);
+        assert!((props.inertia_tensor[2][2] - props.mass * 13.0 / 12.0).abs() < 1e-9);
+        assert!(props.inertia_tensor[0][1].abs() < 1e-9);
+        assert!((props.principal_moments[0] - props.mass * 13.0 / 12.0).abs() < 1e-9);
+        assert!((props.principal_axes[0].z.abs() - 1.0).abs() < 1e-9);
+        assert_eq!(props.bbox_max, Point3::new(2.0, 3.0, 4.0));
+    }
 }
@@ -0,0 +1,1055 @@
+//! Edge fillets and chamfers on solids
+//!
+//! [`BlendOperation`] rounds or bevels chosen edges of a closed faceted
+//! solid. The faces on either side of a blended edge are trimmed back to
+//! setback lines and the gap is bridged by a strip of faces along the edge:
+//! a faceted arc for a fillet, a single band for a chamfer. Where a strip
+//! ends on an untouched face the end is cut into that face, strips running
+//! on along a tangent chain share a mitred section, and the opening left
+//! where several blends meet at a vertex is closed with a corner patch,
+//! spherical when the fillet arcs around it share a centre.
+//! [`edge_chain`] collects the crease edges continuing a seed edge so a
+//! whole tangent chain can be blended at once.
+
+use super::mesh::{EdgeHandle, FaceHandle, HalfEdgeMesh, VertexHandle};
+use super::topology::{newell_normal, ring_edges, ShellSolid, TopologyError, SMOOTH_EDGE_ANGLE};
+use crate::core::{Point3, Vector3, EPSILON};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::PI;
+
+/// Default number of facets across a fillet
+pub const DEFAULT_FILLET_SEGMENTS: usize = 8;
+
+/// Default largest turn between consecutive edges of a chain, enough to
+/// follow a circle faceted with eight or more sides
+pub const DEFAULT_CHAIN_TURN: f64 = PI / 4.0;
+
+/// How one edge is blended
+///
+/// Ends and sides follow the edge's half-edge: the start is its source
+/// vertex and the first face is the one to its left.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum EdgeBlend {
+    /// Round the edge, the radius running linearly from start to end
+    Fillet { start_radius: f64, end_radius: f64 },
+    /// Bevel the edge, cutting back `first` along the first face and
+    /// `second` along the other
+    Chamfer { first: f64, second: f64 },
+    /// Bevel the edge, cutting back `distance` along the first face with
+    /// the bevel at `angle` (radians) to it
+    ChamferAngle { distance: f64, angle: f64 },
+}
+
+impl EdgeBlend {
+    /// Constant-radius fillet
+    pub fn fillet(radius: f64) -> Self {
+        EdgeBlend::Fillet { start_radius: radius, end_radius: radius }
+    }
+
+    /// Fillet whose radius varies along the edge
+    pub fn variable_fillet(start_radius: f64, end_radius: f64) -> Self {
+        EdgeBlend::Fillet { start_radius, end_radius }
+    }
+
+    /// Symmetric chamfer
+    pub fn chamfer(distance: f64) -> Self {
+        EdgeBlend::Chamfer { first: distance, second: distance }
+    }
+
+    /// Chamfer with a distance along each face
+    pub fn chamfer_distances(first: f64, second: f64) -> Self {
+        EdgeBlend::Chamfer { first, second }
+    }
+
+    /// Chamfer given by a distance and an angle to the first face
+    pub fn chamfer_angle(distance: f64, angle: f64) -> Self {
+        EdgeBlend::ChamferAngle { distance, angle }
+    }
+
+    /// Whether the blend fits an edge whose face normals are `angle` apart
+    fn is_valid(&self, angle: f64) -> bool {
+        let positive = |value: f64| value.is_finite() && value > EPSILON;
+        match *self {
+            EdgeBlend::Fillet { start_radius, end_radius } => positive(start_radius) && positive(end_radius),
+            EdgeBlend::Chamfer { first, second } => positive(first) && positive(second),
+            EdgeBlend::ChamferAngle { distance, angle: bevel } => {
+                positive(distance) && positive(bevel) && angle - bevel > EPSILON
+            }
+        }
+    }
+
+    /// Setbacks from the edge along the first and the second face, each at
+    /// the start and the end
+    fn setbacks(&self, angle: f64) -> ([f64; 2], [f64; 2]) {
+        match *self {
+            EdgeBlend::Fillet { start_radius, end_radius } => {
+                let tangent = (angle / 2.0).tan();
+                let setback = [start_radius * tangent, end_radius * tangent];
+                (setback, setback)
+            }
+            EdgeBlend::Chamfer { first, second } => ([first; 2], [second; 2]),
+            EdgeBlend::ChamferAngle { distance, angle: bevel } => {
+                let second = distance * bevel.sin() / (angle - bevel).sin();
+                ([distance; 2], [second; 2])
+            }
+        }
+    }
+}
+
+/// Fillet and chamfer edges of a closed solid
+#[derive(Debug, Clone)]
+pub struct BlendOperation {
+    /// Edges to blend; a later entry for the same edge replaces an earlier one
+    pub edges: Vec<(EdgeHandle, EdgeBlend)>,
+    /// Facets across each fillet
+    pub segments: usize,
+}
+
+impl Default for BlendOperation {
+    fn default() -> Self {
+        Self {
+            edges: Vec::new(),
+            segments: DEFAULT_FILLET_SEGMENTS,
+        }
+    }
+}
+
+impl BlendOperation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Blend these edges as well
+    pub fn with_edges(mut self, edges: impl IntoIterator<Item = (EdgeHandle, EdgeBlend)>) -> Self {
+        self.edges.extend(edges);
+        self
+    }
+
+    /// Facet fillets with `segments` faces across
+    pub fn with_segments(mut self, segments: usize) -> Self {
+        self.segments = segments.max(1);
+        self
+    }
+
+    /// Blend the edges of a closed solid, returning the new solid
+    ///
+    /// Edges are handles into `mesh`. Fails with
+    /// [`TopologyError::BlendTooLarge`] when a face would be trimmed past
+    /// its far side, and [`TopologyError::FlatEdge`] for an edge between
+    /// coplanar faces.
+    pub fn apply(&self, mesh: &HalfEdgeMesh) -> Result<HalfEdgeMesh, TopologyError> {
+        let ends = edge_ends(mesh);
+        let mut selected = Vec::with_capacity(self.edges.len());
+        for &(handle, blend) in &self.edges {
+            let &(from, to) = ends.get(&handle).ok_or(TopologyError::UnknownEdge(handle.0))?;
+            selected.push((handle, from, to, blend));
+        }
+        let solid = ShellSolid::new(mesh, &[])?;
+        let handles: HashMap<(VertexHandle, VertexHandle), EdgeHandle> =
+            ends.iter().map(|(&handle, &ends)| (ends, handle)).collect();
+
+        let mut blends: Vec<Blended> = Vec::new();
+        let mut by_edge: HashMap<(VertexHandle, VertexHandle), usize> = HashMap::new();
+        for (handle, from, to, blend) in selected {
+            let blended = Blended::new(&solid, handle, from, to, blend)?;
+            match by_edge.get(&undirected(from, to)) {
+                Some(&index) => blends[index] = blended,
+                None => {
+                    by_edge.insert(undirected(from, to), blends.len());
+                    blends.push(blended);
+                }
+            }
+        }
+
+        let mut setbacks = HashMap::new();
+        for blended in &blends {
+            let (first, second) = blended.blend.setbacks(blended.angle);
+            setbacks.insert((blended.from, blended.to), (first[0], first[1]));
+            setbacks.insert((blended.to, blended.from), (second[1], second[0]));
+        }
+        let trim = Trim { solid: &solid, setbacks };
+
+        let scale = solid.positions.values().map(|p| p.coords.amax()).fold(1.0, f64::max);
+        let mut points = PointArena::new(1e-9 * scale);
+
+        // Trimmed corner of every face at every vertex
+        let mut faces: Vec<_> = solid.faces.iter().collect();
+        faces.sort_by_key(|(face, _)| face.0);
+        let mut corners = HashMap::new();
+        for &(&face, vertices) in &faces {
+            let trimmed: Vec<Point3> = (0..vertices.len())
+                .map(|i| {
+                    let prev = vertices[(i + vertices.len() - 1) % vertices.len()];
+                    trim.corner(face, prev, vertices[i], vertices[(i + 1) % vertices.len()])
+                })
+                .collect();
+            for (i, (u, w)) in ring_edges(vertices).enumerate() {
+                let along = trimmed[(i + 1) % trimmed.len()] - trimmed[i];
+                if along.dot(&(solid.positions[&w] - solid.positions[&u])) < -points.tolerance {
+                    let prev = vertices[(i + vertices.len() - 1) % vertices.len()];
+                    let next = vertices[(i + 2) % vertices.len()];
+                    let culprit = [(u, w), (prev, u), (w, next)]
+                        .into_iter()
+                        .find(|&(a, b)| by_edge.contains_key(&undirected(a, b)))
+                        .unwrap_or((u, w));
+                    let handle = handles.get(&culprit).map_or(0, |h| h.0);
+                    return Err(TopologyError::BlendTooLarge(handle));
+                }
+            }
+            for (&v, point) in vertices.iter().zip(trimmed) {
+                corners.insert((face, v), points.insert(point));
+            }
+        }
+
+        // Sections across each strip at both its ends, first face side first
+        let mut arcs = HashMap::new();
+        let mut sections: Vec<[Vec<usize>; 2]> = Vec::with_capacity(blends.len());
+        let mut uncapped: Vec<[Option<Section>; 2]> = Vec::with_capacity(blends.len());
+        for blended in &blends {
+            let mut ids: [Vec<usize>; 2] = Default::default();
+            let mut open: [Option<Section>; 2] = Default::default();
+            for end in 0..2 {
+                let x = if end == 0 { blended.from } else { blended.to };
+                let (first, second) = (corners[&(blended.first, x)], corners[&(blended.second, x)]);
+                let (a, b) = (points.get(first), points.get(second));
+                let mut section = blended.section(&solid, a, b, self.segments);
+                let end_face = blended.end_face(&solid, end, &by_edge);
+                if let Some(face) = end_face {
+                    project(&mut section, &blended.direction, &solid.positions[&x], &solid.normals[&face]);
+                }
+                ids[end] = insert_section(&section, first, second, &mut points, &mut arcs);
+                if end_face.is_none() && section.len() > 2 {
+                    open[end] = Some(section);
+                }
+            }
+            sections.push(ids);
+            uncapped.push(open);
+        }
+
+        // Strips running on along a chain share one mitred section
+        let mut joints: HashMap<(VertexHandle, usize, usize), Vec<(usize, usize)>> = HashMap::new();
+        for (index, blended) in blends.iter().enumerate() {
+            for end in (0..2).filter(|&end| uncapped[index][end].is_some()) {
+                let ids = &sections[index][end];
+                let (a, b) = (ids[0], ids[ids.len() - 1]);
+                let x = if end == 0 { blended.from } else { blended.to };
+                joints.entry((x, a.min(b), a.max(b))).or_default().push((index, end));
+            }
+        }
+        let mut joints: Vec<_> = joints.into_iter().filter(|(_, strips)| strips.len() == 2).collect();
+        joints.sort_by_key(|((x, a, b), _)| (x.0, *a, *b));
+        for ((x, _, _), strips) in joints {
+            let [(a, end_a), (b, end_b)] = [strips[0], strips[1]];
+            if sections[a][end_a].len() != sections[b][end_b].len() {
+                continue;
+            }
+            let arriving = if end_a == 1 { blends[a].direction } else { -blends[a].direction };
+            let leaving = if end_b == 0 { blends[b].direction } else { -blends[b].direction };
+            let miter = (arriving + leaving).try_normalize(EPSILON);
+            let (Some(miter), Some(mut section)) = (miter, uncapped[a][end_a].take()) else {
+                continue;
+            };
+            project(&mut section, &arriving, &solid.positions[&x], &miter);
+            let ids = &sections[a][end_a];
+            let ids = insert_section(&section, ids[0], ids[ids.len() - 1], &mut points, &mut arcs);
+            sections[b][end_b] = if ids[0] == sections[b][end_b][0] {
+                ids.clone()
+            } else {
+                ids.iter().rev().copied().collect()
+            };
+            sections[a][end_a] = ids;
+        }
+
+        let mut polygons: Vec<Vec<usize>> = faces
+            .iter()
+            .map(|&(&face, vertices)| vertices.iter().map(|&v| corners[&(face, v)]).collect())
+            .collect();
+        for [at_from, at_to] in &sections {
+            for k in 0..at_from.len() - 1 {
+                polygons.push(vec![at_to[k], at_from[k], at_from[k + 1], at_to[k + 1]]);
+            }
+        }
+        let polygons = close(polygons, &mut points, &arcs, self.segments)?;
+
+        let mut result = HalfEdgeMesh::new();
+        let mut vertices = HashMap::new();
+        for polygon in &polygons {
+            let face: Vec<VertexHandle> = polygon
+                .iter()
+                .map(|&id| *vertices.entry(id).or_insert_with(|| result.add_vertex(points.get(id))))
+                .collect();
+            result.add_face(&face).map_err(|_| TopologyError::MeshCreationFailed)?;
+        }
+        result.update_vertex_normals();
+        Ok(result)
+    }
+}
+
+/// Fillets along a chain, the radius running from `start_radius` to `end_radius`
+///
+/// The radius varies with length along the chain, so consecutive edges meet
+/// with the same radius. Fails with [`TopologyError::BrokenChain`] when the
+/// edges don't follow on from one another, as [`edge_chain`] returns them.
+pub fn variable_fillet_chain(
+    mesh: &HalfEdgeMesh,
+    chain: &[EdgeHandle],
+    start_radius: f64,
+    end_radius: f64,
+) -> Result<Vec<(EdgeHandle, EdgeBlend)>, TopologyError> {
+    let ends = edge_ends(mesh);
+    let mut lengths = Vec::with_capacity(chain.len());
+    let position = |v: VertexHandle| {
+        mesh.get_vertex(v).map(|v| v.position).map_err(|_| TopologyError::InvalidMesh)
+    };
+    let mut previous: Option<VertexHandle> = None;
+    for &handle in chain {
+        let &(from, to) = ends.get(&handle).ok_or(TopologyError::UnknownEdge(handle.0))?;
+        if previous.is_some_and(|v| v != from) {
+            return Err(TopologyError::BrokenChain);
+        }
+        previous = Some(to);
+        lengths.push((position(to)? - position(from)?).norm());
+    }
+
+    let total: f64 = lengths.iter().sum();
+    let radius = |length: f64| {
+        let t = if total > EPSILON { length / total } else { 0.0 };
+        start_radius + (end_radius - start_radius) * t
+    };
+    let mut along = 0.0;
+    Ok(chain
+        .iter()
+        .zip(lengths)
+        .map(|(&handle, length)| {
+            let blend = EdgeBlend::variable_fillet(radius(along), radius(along + length));
+            along += length;
+            (handle, blend)
+        })
+        .collect())
+}
+
+/// Crease edges continuing `seed` on both sides, turning at most `max_turn`
+///
+/// At each vertex the chain follows the crease turning least from the
+/// edge before it, so a faceted circular rim comes back whole while a box
+/// edge stays on its own. Handles are ordered and oriented along the chain.
+pub fn edge_chain(
+    mesh: &HalfEdgeMesh,
+    seed: EdgeHandle,
+    max_turn: f64,
+) -> Result<Vec<EdgeHandle>, TopologyError> {
+    let ends = edge_ends(mesh);
+    let &(from, to) = ends.get(&seed).ok_or(TopologyError::UnknownEdge(seed.0))?;
+    let solid = ShellSolid::new(mesh, &[])?;
+    let handles: HashMap<(VertexHandle, VertexHandle), EdgeHandle> =
+        ends.iter().map(|(&handle, &ends)| (ends, handle)).collect();
+
+    let mut neighbours: HashMap<VertexHandle, Vec<VertexHandle>> = HashMap::new();
+    for &(u, w) in solid.edges.keys() {
+        neighbours.entry(u).or_default().push(w);
+    }
+    for list in neighbours.values_mut() {
+        list.sort_by_key(|v| v.0);
+    }
+    let is_crease = |u: VertexHandle, w: VertexHandle| {
+        let (a, b) = (solid.normals[&solid.edges[&(u, w)]], solid.normals[&solid.edges[&(w, u)]]);
+        a.dot(&b).clamp(-1.0, 1.0).acos() >= SMOOTH_EDGE_ANGLE
+    };
+    let direction = |u: VertexHandle, w: VertexHandle| {
+        (solid.positions[&w] - solid.positions[&u]).normalize()
+    };
+    let turn = |a: Vector3, b: Vector3| a.dot(&b).clamp(-1.0, 1.0).acos();
+
+    let mut used = HashSet::from([undirected(from, to)]);
+    // Least-turning unused crease leaving `at`, measured against `heading`
+    let mut step = |at: VertexHandle, heading: Vector3, outgoing: bool| {
+        let best = neighbours[&at]
+            .iter()
+            .filter(|&&x| !used.contains(&undirected(at, x)) && is_crease(at, x))
+            .map(|&x| {
+                let along = if outgoing { direction(at, x) } else { direction(x, at) };
+                (x, turn(heading, along))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))?;
+        (best.1 <= max_turn + 1e-9).then(|| {
+            used.insert(undirected(at, best.0));
+            best.0
+        })
+    };
+
+    let mut forward = vec![(from, to)];
+    while let Some(&(u, w)) = forward.last() {
+        match step(w, direction(u, w), true) {
+            Some(x) => forward.push((w, x)),
+            None => break,
+        }
+    }
+    let mut backward = Vec::new();
+    let mut first = (from, to);
+    while let Some(x) = step(first.0, direction(first.0, first.1), false) {
+        first = (x, first.0);
+        backward.push(first);
+    }
+
+    backward
+        .into_iter()
+        .rev()
+        .chain(forward)
+        .map(|edge| handles.get(&edge).copied().ok_or(TopologyError::InvalidMesh))
+        .collect()
+}
+
+/// Source and target vertex of every edge's half-edge
+fn edge_ends(mesh: &HalfEdgeMesh) -> HashMap<EdgeHandle, (VertexHandle, VertexHandle)> {
+    mesh.halfedges
+        .iter()
+        .flatten()
+        .filter_map(|he| {
+            let prev = mesh.halfedges.get(he.prev.0)?.as_ref()?;
+            Some((he.edge, (prev.vertex, he.vertex)))
+        })
+        .collect()
+}
+
+fn undirected(u: VertexHandle, w: VertexHandle) -> (VertexHandle, VertexHandle) {
+    if u.0 <= w.0 {
+        (u, w)
+    } else {
+        (w, u)
+    }
+}
+
+/// Centre and radius of the fillet arc a point lies on
+type Arc = (Point3, f64);
+
+/// Points across a strip, with the arc each lies on
+type Section = Vec<(Point3, Option<Arc>)>;
+
+/// A selected edge, oriented along its half-edge
+struct Blended {
+    from: VertexHandle,
+    to: VertexHandle,
+    /// Face to the left of the edge
+    first: FaceHandle,
+    second: FaceHandle,
+    blend: EdgeBlend,
+    /// Angle between the face normals
+    angle: f64,
+    /// 1 on a convex edge, -1 on a concave one
+    sign: f64,
+    /// Unit direction from `from` to `to`
+    direction: Vector3,
+    length: f64,
+}
+
+impl Blended {
+    fn new(
+        solid: &ShellSolid,
+        handle: EdgeHandle,
+        from: VertexHandle,
+        to: VertexHandle,
+        blend: EdgeBlend,
+    ) -> Result<Self, TopologyError> {
+        let first = solid.edges[&(from, to)];
+        let second = solid.edges[&(to, from)];
+        let (n_first, n_second) = (solid.normals[&first], solid.normals[&second]);
+        let angle = n_first.dot(&n_second).clamp(-1.0, 1.0).acos();
+        if angle < 1e-6 || PI - angle < 1e-6 {
+            return Err(TopologyError::FlatEdge(handle.0));
+        }
+        if !blend.is_valid(angle) {
+            return Err(TopologyError::InvalidBlendSize);
+        }
+        let edge = solid.positions[&to] - solid.positions[&from];
+        let direction = edge.normalize();
+        let inward = n_first.cross(&direction);
+        Ok(Self {
+            from,
+            to,
+            first,
+            second,
+            blend,
+            angle,
+            sign: if n_second.dot(&inward) < 0.0 { 1.0 } else { -1.0 },
+            direction,
+            length: edge.norm(),
+        })
+    }
+
+    /// Points across the blend between the trimmed corners of the first and
+    /// second face at one end
+    ///
+    /// Fillet points slide along the edge with the corners, so a section
+    /// between corners set back by different amounts stays on the rolling
+    /// ball's path.
+    fn section(&self, solid: &ShellSolid, first: Point3, second: Point3, segments: usize) -> Section {
+        let EdgeBlend::Fillet { start_radius, end_radius } = self.blend else {
+            return vec![(first, None), (second, None)];
+        };
+        let origin = solid.positions[&self.from];
+        let (n_first, n_second) = (solid.normals[&self.first], solid.normals[&self.second]);
+        let inward = n_first.cross(&self.direction);
+        let station = |p: Point3| (p - origin).dot(&self.direction);
+        let (a, b) = (station(first), station(second));
+        (0..=segments)
+            .map(|k| {
+                let f = k as f64 / segments as f64;
+                let along = a + (b - a) * f;
+                let t = if self.length > EPSILON { (along / self.length).clamp(0.0, 1.0) } else { 0.0 };
+                let radius = start_radius + (end_radius - start_radius) * t;
+                let setback = radius * (self.angle / 2.0).tan();
+                let foot = origin + self.direction * along + inward * setback;
+                let centre = foot - n_first * (self.sign * radius);
+                let toward = (n_first * ((1.0 - f) * self.angle).sin() + n_second * (f * self.angle).sin())
+                    / self.angle.sin();
+                let point = match k {
+                    0 => first,
+                    k if k == segments => second,
+                    _ => centre + toward * (self.sign * radius),
+                };
+                (point, Some((centre, radius)))
+            })
+            .collect()
+    }
+
+    /// Untouched face the strip ends on at one end, if the faces beyond
+    /// both sides of the edge are that same face
+    fn end_face(
+        &self,
+        solid: &ShellSolid,
+        end: usize,
+        selected: &HashMap<(VertexHandle, VertexHandle), usize>,
+    ) -> Option<FaceHandle> {
+        let around = |face: FaceHandle, v: VertexHandle| {
+            let vertices = &solid.faces[&face];
+            let i = vertices.iter().position(|&u| u == v)?;
+            Some((vertices[(i + vertices.len() - 1) % vertices.len()], vertices[(i + 1) % vertices.len()]))
+        };
+        let (x, beyond_first, beyond_second) = if end == 0 {
+            let (p, _) = around(self.first, self.from)?;
+            let (_, q) = around(self.second, self.from)?;
+            (self.from, p, q)
+        } else {
+            let (_, q) = around(self.first, self.to)?;
+            let (p, _) = around(self.second, self.to)?;
+            (self.to, q, p)
+        };
+        let face = *solid.edges.get(&(beyond_first, x))?;
+        let other = *solid.edges.get(&(x, beyond_second))?;
+        let untouched = !selected.contains_key(&undirected(x, beyond_first))
+            && !selected.contains_key(&undirected(x, beyond_second));
+        (face == other && face != self.first && face != self.second && untouched).then_some(face)
+    }
+}
+
+/// Ids of a section's points, its ends being the trimmed corners given
+fn insert_section(
+    section: &[(Point3, Option<Arc>)],
+    first: usize,
+    second: usize,
+    points: &mut PointArena,
+    arcs: &mut HashMap<usize, Arc>,
+) -> Vec<usize> {
+    let last = section.len() - 1;
+    section
+        .iter()
+        .enumerate()
+        .map(|(k, (point, arc))| {
+            let id = match k {
+                0 => first,
+                k if k == last => second,
+                _ => points.insert(*point),
+            };
+            if let Some(arc) = arc {
+                arcs.insert(id, *arc);
+            }
+            id
+        })
+        .collect()
+}
+
+/// Moves the inner points of a section along `direction` onto a plane
+fn project(section: &mut [(Point3, Option<Arc>)], direction: &Vector3, origin: &Point3, normal: &Vector3) {
+    let across = direction.dot(normal);
+    if across.abs() < 1e-6 || section.len() < 3 {
+        return;
+    }
+    let last = section.len() - 1;
+    for (point, _) in &mut section[1..last] {
+        *point += direction * ((origin - *point).dot(normal) / across);
+    }
+}
+
+/// Faces of a solid trimmed back to the setback lines of blended edges
+struct Trim<'a> {
+    solid: &'a ShellSolid,
+    /// Setback at each end of a directed edge into the face to its left
+    setbacks: HashMap<(VertexHandle, VertexHandle), (f64, f64)>,
+}
+
+impl Trim<'_> {
+    /// Boundary line of `face` along the edge from `u` to `w`, and whether
+    /// it is set back
+    fn line(&self, face: FaceHandle, u: VertexHandle, w: VertexHandle) -> (Point3, Vector3, bool) {
+        let (pu, pw) = (self.solid.positions[&u], self.solid.positions[&w]);
+        let along = (pw - pu).normalize();
+        match self.setbacks.get(&(u, w)) {
+            None => (pu, along, false),
+            Some(&(at_u, at_w)) => {
+                let inward = self.solid.normals[&face].cross(&along);
+                let (a, b) = (pu + inward * at_u, pw + inward * at_w);
+                (a, (b - a).normalize(), true)
+            }
+        }
+    }
+
+    /// Where the trimmed boundary of `face` turns at `v`
+    fn corner(&self, face: FaceHandle, prev: VertexHandle, v: VertexHandle, next: VertexHandle) -> Point3 {
+        let (p1, a, in_set) = self.line(face, prev, v);
+        let (p2, b, out_set) = self.line(face, v, next);
+        let position = self.solid.positions[&v];
+        if !in_set && !out_set {
+            return position;
+        }
+        let normal = a.cross(&b);
+        if normal.norm_squared() < 1e-18 {
+            let (p, d) = if in_set { (p1, a) } else { (p2, b) };
+            return p + d * (position - p).dot(&d);
+        }
+        p1 + a * ((p2 - p1).cross(&b).dot(&normal) / normal.norm_squared())
+    }
+}
+
+/// Points of the new solid, merged within a tolerance
+struct PointArena {
+    points: Vec<Point3>,
+    cells: HashMap<[i64; 3], Vec<usize>>,
+    tolerance: f64,
+}
+
+impl PointArena {
+    fn new(tolerance: f64) -> Self {
+        Self {
+            points: Vec::new(),
+            cells: HashMap::new(),
+            tolerance,
+        }
+    }
+
+    fn cell(&self, point: &Point3) -> [i64; 3] {
+        [0, 1, 2].map(|i| (point[i] / self.tolerance).floor() as i64)
+    }
+
+    fn insert(&mut self, point: Point3) -> usize {
+        let [x, y, z] = self.cell(&point);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let near = self.cells.get(&[x + dx, y + dy, z + dz]).and_then(|ids| {
+                        ids.iter().find(|&&id| (self.points[id] - point).norm() <= self.tolerance)
+                    });
+                    if let Some(&id) = near {
+                        return id;
+                    }
+                }
+            }
+        }
+        let id = self.points.len();
+        self.points.push(point);
+        self.cells.entry([x, y, z]).or_default().push(id);
+        id
+    }
+
+    fn get(&self, id: usize) -> Point3 {
+        self.points[id]
+    }
+}
+
+/// Closes the openings left between trimmed faces and strips
+///
+/// Points lying along an unmatched edge are threaded into it first. Each
+/// remaining opening is merged into a coplanar neighbour when flat, added
+/// as a face of its own when flat but not, and patched otherwise.
+fn close(
+    polygons: Vec<Vec<usize>>,
+    points: &mut PointArena,
+    arcs: &HashMap<usize, Arc>,
+    segments: usize,
+) -> Result<Vec<Vec<usize>>, TopologyError> {
+    let mut polygons: Vec<Vec<usize>> = polygons.into_iter().filter_map(tidy).collect();
+    split_at_breakpoints(&mut polygons, points);
+
+    for hole in holes(&polygons)? {
+        let ring: Vec<Point3> = hole.iter().map(|&id| points.get(id)).collect();
+        let normal = newell_normal(&ring);
+        let Some(normal) = normal.try_normalize(EPSILON * EPSILON) else {
+            return Err(TopologyError::MeshCreationFailed);
+        };
+        let centroid = centroid(&ring);
+        let flat_tolerance = points.tolerance * 1e3;
+        if ring.iter().all(|p| (p - centroid).dot(&normal).abs() <= flat_tolerance) {
+            let merged = polygons.iter_mut().any(|polygon| {
+                let outline: Vec<Point3> = polygon.iter().map(|&id| points.get(id)).collect();
+                let coplanar = newell_normal(&outline)
+                    .try_normalize(EPSILON * EPSILON)
+                    .is_some_and(|n| {
+                        n.dot(&normal) > 1.0 - 1e-9 && (outline[0] - centroid).dot(&n).abs() <= flat_tolerance
+                    });
+                coplanar && merge(polygon, &hole)
+            });
+            if !merged {
+                polygons.push(hole);
+            }
+        } else {
+            polygons.extend(corner_patch(&hole, points, arcs, segments));
+        }
+    }
+
+    let edges: HashSet<(usize, usize)> = polygons.iter().flat_map(|p| ring_pairs(p)).collect();
+    if edges.iter().any(|&(a, b)| !edges.contains(&(b, a))) {
+        return Err(TopologyError::MeshCreationFailed);
+    }
+    Ok(polygons)
+}
+
+fn centroid(ring: &[Point3]) -> Point3 {
+    Point3::from(ring.iter().fold(Vector3::zeros(), |acc, p| acc + p.coords) / ring.len() as f64)
+}
+
+/// Drops repeated points from a polygon, and the polygon if it collapses
+fn tidy(mut polygon: Vec<usize>) -> Option<Vec<usize>> {
+    polygon.dedup();
+    while polygon.len() > 1 && polygon.first() == polygon.last() {
+        polygon.pop();
+    }
+    (polygon.len() >= 3).then_some(polygon)
+}
+
+fn ring_pairs(polygon: &[usize]) -> impl Iterator<Item = (usize, usize)> + '_ {
+    polygon.iter().enumerate().map(|(i, &a)| (a, polygon[(i + 1) % polygon.len()]))
+}
+
+/// Threads points lying along unmatched edges into those edges
+fn split_at_breakpoints(polygons: &mut [Vec<usize>], points: &PointArena) {
+    let edges: HashSet<(usize, usize)> = polygons.iter().flat_map(|p| ring_pairs(p)).collect();
+    let open: HashSet<(usize, usize)> =
+        edges.iter().copied().filter(|&(a, b)| !edges.contains(&(b, a))).collect();
+    let mut candidates: Vec<usize> = open.iter().flat_map(|&(a, b)| [a, b]).collect();
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    for polygon in polygons.iter_mut() {
+        let mut threaded = Vec::with_capacity(polygon.len());
+        for (a, b) in ring_pairs(polygon) {
+            threaded.push(a);
+            if !open.contains(&(a, b)) {
+                continue;
+            }
+            let (pa, pb) = (points.get(a), points.get(b));
+            let along = pb - pa;
+            let length = along.norm_squared();
+            let mut inside: Vec<(f64, usize)> = candidates
+                .iter()
+                .filter(|&&c| c != a && c != b)
+                .filter_map(|&c| {
+                    let offset = points.get(c) - pa;
+                    let t = offset.dot(&along) / length;
+                    let off_line = (offset - along * t).norm();
+                    (t > 0.0 && t < 1.0 && off_line <= points.tolerance).then_some((t, c))
+                })
+                .collect();
+            inside.sort_by(|x, y| x.0.total_cmp(&y.0));
+            threaded.extend(inside.into_iter().map(|(_, c)| c));
+        }
+        *polygon = threaded;
+    }
+}
+
+/// Loops of unmatched edges, reversed so each can be filled as a face
+fn holes(polygons: &[Vec<usize>]) -> Result<Vec<Vec<usize>>, TopologyError> {
+    let mut edges: HashSet<(usize, usize)> = HashSet::new();
+    for (a, b) in polygons.iter().flat_map(|p| ring_pairs(p)) {
+        if !edges.insert((a, b)) {
+            return Err(TopologyError::MeshCreationFailed);
+        }
+    }
+    let mut next: HashMap<usize, Vec<usize>> = HashMap::new();
+    for &(a, b) in &edges {
+        if !edges.contains(&(b, a)) {
+            next.entry(b).or_default().push(a);
+        }
+    }
+    for targets in next.values_mut() {
+        targets.sort_unstable_by(|x, y| y.cmp(x));
+    }
+
+    let mut starts: Vec<usize> = next.keys().copied().collect();
+    starts.sort_unstable();
+    let mut loops = Vec::new();
+    for start in starts {
+        while next.get(&start).is_some_and(|t| !t.is_empty()) {
+            let mut hole = vec![start];
+            let mut at = start;
+            loop {
+                at = next.get_mut(&at).and_then(|t| t.pop()).ok_or(TopologyError::MeshCreationFailed)?;
+                if at == start {
+                    break;
+                }
+                hole.push(at);
+            }
+            loops.push(tidy(hole).ok_or(TopologyError::MeshCreationFailed)?);
+        }
+    }
+    Ok(loops)
+}
+
+/// Merges a hole into a polygon across the one run of edges they share
+fn merge(polygon: &mut Vec<usize>, hole: &[usize]) -> bool {
+    let hole_edges: HashSet<(usize, usize)> = ring_pairs(hole).collect();
+    let shared: Vec<bool> = ring_pairs(polygon).map(|(a, b)| hole_edges.contains(&(b, a))).collect();
+    let n = polygon.len();
+    let starts: Vec<usize> = (0..n).filter(|&i| shared[i] && !shared[(i + n - 1) % n]).collect();
+    let [start] = starts[..] else {
+        return false;
+    };
+    let run = (0..n).take_while(|k| shared[(start + k) % n]).count();
+
+    // The polygon from the run's last point round to its first, then the
+    // hole from the run's first point round to its last
+    let (first, last) = (polygon[start], polygon[(start + run) % n]);
+    let Some(at) = hole.iter().position(|&id| id == first) else {
+        return false;
+    };
+    let mut merged: Vec<usize> = (0..=n - run).map(|k| polygon[(start + run + k) % n]).collect();
+    let m = hole.len();
+    for k in 1..m {
+        let id = hole[(at + k) % m];
+        if id == last {
+            break;
+        }
+        merged.push(id);
+    }
+    match tidy(merged) {
+        Some(merged) if merged.iter().collect::<HashSet<_>>().len() == merged.len() => {
+            *polygon = merged;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Faces closing an opening that isn't flat, where blends meet at a vertex
+///
+/// Rings shrink from the opening toward its middle; when the fillet arcs
+/// around the opening share a centre the rings lie on that sphere,
+/// otherwise the opening is closed with a fan.
+fn corner_patch(
+    hole: &[usize],
+    points: &mut PointArena,
+    arcs: &HashMap<usize, Arc>,
+    segments: usize,
+) -> Vec<Vec<usize>> {
+    let ring: Vec<Point3> = hole.iter().map(|&id| points.get(id)).collect();
+    let centroid = centroid(&ring);
+
+    let spheres: Option<Vec<Arc>> = hole.iter().map(|id| arcs.get(id).copied()).collect();
+    let sphere = spheres.and_then(|spheres| {
+        let (centre, radius) = spheres[0];
+        let close = points.tolerance * 1e3;
+        let agree = spheres.iter().all(|(c, r)| (c - centre).norm() <= close && (r - radius).abs() <= close);
+        agree.then_some((centre, radius))
+    });
+    let onto = |p: Point3| match sphere {
+        Some((centre, radius)) => (p - centre).try_normalize(EPSILON).map_or(p, |d| centre + d * radius),
+        None => p,
+    };
+
+    let rings = if sphere.is_some() { (segments / 2).max(1) } else { 1 };
+    let mut faces = Vec::new();
+    let mut outer = hole.to_vec();
+    for step in 1..rings {
+        let t = step as f64 / rings as f64;
+        let inner: Vec<usize> = ring.iter().map(|p| points.insert(onto(p + (centroid - p) * t))).collect();
+        for (j, (&a, &b)) in outer.iter().zip(outer.iter().cycle().skip(1)).enumerate() {
+            faces.push(vec![a, b, inner[(j + 1) % inner.len()], inner[j]]);
+        }
+        outer = inner;
+    }
+    let apex = points.insert(onto(centroid));
+    for (&a, &b) in outer.iter().zip(outer.iter().cycle().skip(1)) {
+        faces.push(vec![a, b, apex]);
+    }
+    faces.into_iter().filter_map(tidy).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine3d::boolean::MeshValidity;
+    use crate::engine3d::topology::ExtrudeOperation;
+
+    fn prism(profile: &[(f64, f64)], height: f64) -> HalfEdgeMesh {
+        let profile: Vec<Point3> = profile.iter().map(|&(x, y)| Point3::new(x, y, 0.0)).collect();
+        let extrude = ExtrudeOperation {
+            direction: Vector3::new(0.0, 0.0, height),
+            capped: true,
+            ..Default::default()
+        };
+        extrude.extrude_profile(&profile).unwrap()
+    }
+
+    fn cube() -> HalfEdgeMesh {
+        prism(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)], 2.0)
+    }
+
+    fn volume(mesh: &HalfEdgeMesh) -> f64 {
+        let validity = MeshValidity::check(mesh).unwrap();
+        assert!(validity.is_valid(), "{:?}", validity);
+        validity.volume
+    }
+
+    fn blend(
+        mesh: &HalfEdgeMesh,
+        edges: impl IntoIterator<Item = (EdgeHandle, EdgeBlend)>,
+    ) -> Result<HalfEdgeMesh, TopologyError> {
+        BlendOperation::new().with_edges(edges).apply(mesh)
+    }
+
+    /// The edge whose half-edge runs between two points
+    fn edge(mesh: &HalfEdgeMesh, from: [f64; 3], to: [f64; 3]) -> EdgeHandle {
+        let position = |v: VertexHandle| mesh.get_vertex(v).unwrap().position;
+        edge_ends(mesh)
+            .into_iter()
+            .find(|(_, (u, w))| {
+                let near = |v: VertexHandle, p: [f64; 3]| (position(v) - Point3::from(p)).norm() < 1e-9;
+                near(*u, from) && near(*w, to)
+            })
+            .map(|(handle, _)| handle)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fillet_and_chamfer_one_edge() {
+        let mesh = cube();
+        let top = edge(&mesh, [0.0, 0.0, 2.0], [2.0, 0.0, 2.0]);
+        let (r, n) = (0.5, 8);
+        let filleted = BlendOperation::new()
+            .with_edges([(top, EdgeBlend::fillet(r))])
+            .with_segments(n)
+            .apply(&mesh)
+            .unwrap();
+        let removed = r * r - 0.5 * n as f64 * r * r * (PI / (2.0 * n as f64)).sin();
+        assert!((volume(&filleted) - (8.0 - 2.0 * removed)).abs() < 1e-9);
+
+        // An even 45 degree chamfer is the symmetric one
+        for (kind, expected) in [
+            (EdgeBlend::chamfer(0.2), 7.96),
+            (EdgeBlend::chamfer_angle(0.2, PI / 4.0), 7.96),
+            (EdgeBlend::chamfer_distances(0.2, 0.4), 7.92),
+        ] {
+            let chamfered = blend(&mesh, [(top, kind)]).unwrap();
+            assert!((volume(&chamfered) - expected).abs() < 1e-9, "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn test_blend_every_edge_of_a_box() {
+        let mesh = cube();
+        let all: Vec<EdgeHandle> = mesh.edge_handles();
+        let (a, r) = (2.0, 0.2);
+        let rounded = blend(&mesh, all.iter().map(|&e| (e, EdgeBlend::fillet(r)))).unwrap();
+        let core = a - 2.0 * r;
+        let exact =
+            core.powi(3) + 6.0 * core * core * r + 3.0 * PI * r * r * core + 4.0 / 3.0 * PI * r.powi(3);
+        let faceted = volume(&rounded);
+        assert!(faceted < exact && exact - faceted < 0.02, "{} vs {}", faceted, exact);
+
+        let bevelled = blend(&mesh, all.iter().map(|&e| (e, EdgeBlend::chamfer(r)))).unwrap();
+        // Two blends meeting at a corner where the third edge stays sharp
+        let front = edge(&mesh, [0.0, 0.0, 2.0], [2.0, 0.0, 2.0]);
+        let left = edge(&mesh, [0.0, 2.0, 2.0], [0.0, 0.0, 2.0]);
+        let mitred = blend(&mesh, [(front, EdgeBlend::chamfer(r)), (left, EdgeBlend::chamfer(r))]).unwrap();
+        assert!((volume(&mitred) - (8.0 - r * r * a + r.powi(3) / 3.0)).abs() < 1e-9);
+        let rounded = blend(&mesh, [(front, EdgeBlend::fillet(r)), (left, EdgeBlend::fillet(r))]).unwrap();
+        let removed = r * r - 4.0 * r * r * (PI / 16.0).sin();
+        let overlap = volume(&rounded) - (8.0 - 2.0 * a * removed);
+        assert!(overlap > 0.0 && overlap < r.powi(3), "{}", overlap);
+
+        // Edge prisms, and at each corner all of the corner cube but a tetrahedron
+        let cut = 6.0 * r * r * (a - 2.0 * r) + 8.0 * (5.0 / 6.0) * r.powi(3);
+        assert!((volume(&bevelled) - (8.0 - cut)).abs() < 1e-9, "{}", volume(&bevelled));
+    }
+
+    #[test]
+    fn test_concave_fillet_adds_material() {
+        let mesh = prism(&[(0.0, 0.0), (2.0, 0.0), (2.0, 1.0), (1.0, 1.0), (1.0, 2.0), (0.0, 2.0)], 1.0);
+        let inner = edge(&mesh, [1.0, 1.0, 0.0], [1.0, 1.0, 1.0]);
+        let (r, n) = (0.25, 8);
+        let filleted = blend(&mesh, [(inner, EdgeBlend::fillet(r))]).unwrap();
+        let added = r * r - 0.5 * n as f64 * r * r * (PI / (2.0 * n as f64)).sin();
+        assert!((volume(&filleted) - (3.0 + added)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_edge_chain_and_variable_fillet() {
+        let rim: Vec<(f64, f64)> = (0..16)
+            .map(|i| {
+                let a = i as f64 * PI / 8.0;
+                (a.cos(), a.sin())
+            })
+            .collect();
+        let mesh = prism(&rim, 1.0);
+        let seed = edge(&mesh, [1.0, 0.0, 1.0], [(PI / 8.0).cos(), (PI / 8.0).sin(), 1.0]);
+        let chain = edge_chain(&mesh, seed, DEFAULT_CHAIN_TURN).unwrap();
+        assert_eq!(chain.len(), 16);
+        assert!(chain.contains(&seed));
+
+        let cube = cube();
+        let single = edge(&cube, [0.0, 0.0, 2.0], [2.0, 0.0, 2.0]);
+        assert_eq!(edge_chain(&cube, single, DEFAULT_CHAIN_TURN).unwrap(), vec![single]);
+
+        let base = volume(&mesh);
+        let constant = blend(&mesh, chain.iter().map(|&e| (e, EdgeBlend::fillet(0.1)))).unwrap();
+        let varying = blend(&mesh, variable_fillet_chain(&mesh, &chain[..8], 0.05, 0.1).unwrap()).unwrap();
+        assert!(volume(&constant) < base);
+        assert!(volume(&varying) < base);
+
+        let broken = [chain[0], chain[2]];
+        assert!(matches!(variable_fillet_chain(&mesh, &broken, 0.1, 0.2), Err(TopologyError::BrokenChain)));
+    }
+
+    #[test]
+    fn test_blend_errors() {
+        let mesh = cube();
+        let top = edge(&mesh, [0.0, 0.0, 2.0], [2.0, 0.0, 2.0]);
+        let top_edge = |kind: EdgeBlend| blend(&mesh, [(top, kind)]);
+        assert!(matches!(top_edge(EdgeBlend::fillet(0.0)), Err(TopologyError::InvalidBlendSize)));
+        let steep = top_edge(EdgeBlend::chamfer_angle(0.2, PI / 2.0));
+        assert!(matches!(steep, Err(TopologyError::InvalidBlendSize)));
+        assert!(matches!(top_edge(EdgeBlend::fillet(2.5)), Err(TopologyError::BlendTooLarge(_))));
+        let unknown = blend(&mesh, [(EdgeHandle(999), EdgeBlend::fillet(0.1))]);
+        assert!(matches!(unknown, Err(TopologyError::UnknownEdge(999))));
+
+        // A box whose top is split into two triangles
+        let mut split = HalfEdgeMesh::new();
+        let v: Vec<VertexHandle> = (0..8)
+            .map(|i| split.add_vertex(Point3::new((i & 1) as f64, ((i >> 1) & 1) as f64, (i >> 2) as f64)))
+            .collect();
+        let faces: [&[usize]; 7] = [
+            &[0, 2, 3, 1],
+            &[4, 5, 7],
+            &[4, 7, 6],
+            &[0, 1, 5, 4],
+            &[2, 6, 7, 3],
+            &[0, 4, 6, 2],
+            &[1, 3, 7, 5],
+        ];
+        for face in faces {
+            split.add_face(&face.iter().map(|&i| v[i]).collect::<Vec<_>>()).unwrap();
+        }
+        let diagonal = edge(&split, [0.0, 0.0, 1.0], [1.0, 1.0, 1.0]);
+        let flat = blend(&split, [(diagonal, EdgeBlend::fillet(0.1))]);
+        assert!(matches!(flat, Err(TopologyError::FlatEdge(_))));
+    }
+}
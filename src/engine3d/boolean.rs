@@ -1,7 +1,11 @@
 //! CSG (Constructive Solid Geometry) Boolean Operations
 //!
-//! Implements union, intersection, and difference operations on meshes
-//! using robust BSP-tree based algorithms with numerical stability.
+//! Implements union, intersection, and difference operations on closed
+//! meshes using BSP-tree clipping. Coincident faces are told apart by the
+//! direction they face, so solids that touch or share faces combine
+//! cleanly. Split edges are stitched back together before the result is
+//! rebuilt, and both the operands and the result are checked for being
+//! closed, consistently oriented solids.
 
 use super::mesh::{HalfEdgeMesh, VertexHandle, FaceHandle, MeshError};
 use crate::core::{Point3, Vector3, EPSILON};
@@ -9,6 +13,9 @@ use crate::core::{Point3, Vector3, EPSILON};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Distance within which points are considered to lie on a splitting plane
+pub const PLANE_EPSILON: f64 = 1e-6;
+
 /// BSP (Binary Space Partitioning) tree for CSG operations
 #[derive(Debug, Clone)]
 pub struct BSPTree {
@@ -54,13 +61,18 @@ impl Plane {
         Some(Self::from_normal_point(normal, p1))
     }
 
+    /// Signed distance from the plane to a point
+    pub fn distance_to(&self, point: &Point3) -> f64 {
+        self.normal.dot(&point.coords) - self.distance
+    }
+
     /// Classify a point relative to the plane
     pub fn classify_point(&self, point: &Point3) -> PointClassification {
-        let dist = self.normal.dot(&point.coords) - self.distance;
+        let dist = self.distance_to(point);
 
-        if dist > EPSILON {
+        if dist > PLANE_EPSILON {
             PointClassification::Front
-        } else if dist < -EPSILON {
+        } else if dist < -PLANE_EPSILON {
             PointClassification::Back
         } else {
             PointClassification::OnPlane
@@ -82,6 +94,9 @@ pub enum PointClassification {
 }
 
 /// Polygon representation for CSG operations
+///
+/// Polygons are expected to be planar and convex, which holds for the
+/// triangles and quads produced by tessellation and the topology operations.
 #[derive(Debug, Clone)]
 pub struct Polygon {
     pub vertices: Vec<Point3>,
@@ -91,12 +106,20 @@ pub struct Polygon {
 
 impl Polygon {
     /// Create a polygon from vertices
+    ///
+    /// The plane normal is taken from the whole outline (Newell's method), so
+    /// leading collinear vertices are fine. Returns `None` for fewer than
+    /// three vertices or a zero-area outline.
     pub fn new(vertices: Vec<Point3>) -> Option<Self> {
         if vertices.len() < 3 {
             return None;
         }
 
-        let plane = Plane::from_points(vertices[0], vertices[1], vertices[2])?;
+        let normal = newell_normal(&vertices);
+        if normal.norm() < EPSILON {
+            return None;
+        }
+        let plane = Plane::from_normal_point(normal, vertices[0]);
 
         Some(Self {
             vertices,
@@ -112,6 +135,9 @@ impl Polygon {
     }
 
     /// Split polygon by a plane
+    ///
+    /// Both halves keep this polygon's plane and original face. Vertices on
+    /// the splitting plane are shared by both halves.
     pub fn split(&self, plane: &Plane) -> PolygonSplit {
         let mut front = Vec::new();
         let mut back = Vec::new();
@@ -144,24 +170,29 @@ impl Polygon {
         }
 
         if front_verts.len() >= 3 {
-            if let Some(poly) = Polygon::new(front_verts) {
-                front.push(poly);
-            }
+            front.push(self.with_vertices(front_verts));
         }
 
         if back_verts.len() >= 3 {
-            if let Some(poly) = Polygon::new(back_verts) {
-                back.push(poly);
-            }
+            back.push(self.with_vertices(back_verts));
         }
 
         PolygonSplit { front, back }
     }
 
+    /// A piece of this polygon with the given outline
+    fn with_vertices(&self, vertices: Vec<Point3>) -> Self {
+        Self {
+            vertices,
+            plane: self.plane,
+            original_face: self.original_face,
+        }
+    }
+
     /// Intersect an edge with a plane
     fn intersect_edge_plane(&self, v1: &Point3, v2: &Point3, plane: &Plane) -> Option<Point3> {
-        let d1 = plane.normal.dot(&v1.coords) - plane.distance;
-        let d2 = plane.normal.dot(&v2.coords) - plane.distance;
+        let d1 = plane.distance_to(v1);
+        let d2 = plane.distance_to(v2);
 
         let denom = d1 - d2;
         if denom.abs() < EPSILON {
@@ -169,7 +200,7 @@ impl Polygon {
         }
 
         let t = d1 / denom;
-        if t < 0.0 || t > 1.0 {
+        if !(0.0..=1.0).contains(&t) {
             return None;
         }
 
@@ -177,6 +208,18 @@ impl Polygon {
     }
 }
 
+/// Unnormalized polygon normal by Newell's method
+fn newell_normal(vertices: &[Point3]) -> Vector3 {
+    let mut normal = Vector3::zeros();
+    for (i, a) in vertices.iter().enumerate() {
+        let b = vertices[(i + 1) % vertices.len()];
+        normal.x += (a.y - b.y) * (a.z + b.z);
+        normal.y += (a.z - b.z) * (a.x + b.x);
+        normal.z += (a.x - b.x) * (a.y + b.y);
+    }
+    normal
+}
+
 #[derive(Debug)]
 pub struct PolygonSplit {
     pub front: Vec<Polygon>,
@@ -192,11 +235,24 @@ impl BSPTree {
     /// Build a BSP tree from polygons
     pub fn from_polygons(polygons: Vec<Polygon>) -> Self {
         let mut tree = Self::new();
-        tree.root = BSPNode::build(polygons);
+        tree.build(polygons);
         tree
     }
 
-    /// Clip polygons to this tree
+    /// Add polygons to the tree, splitting them by the existing planes
+    pub fn build(&mut self, polygons: Vec<Polygon>) {
+        match self.root {
+            Some(ref mut root) => root.build(polygons),
+            None => self.root = BSPNode::new(polygons),
+        }
+    }
+
+    /// Whether the tree holds no planes at all
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Clip polygons to this tree, dropping the parts inside it
     pub fn clip_polygons(&self, polygons: Vec<Polygon>) -> Vec<Polygon> {
         if let Some(ref root) = self.root {
             root.clip_polygons(polygons)
@@ -236,65 +292,81 @@ impl Default for BSPTree {
 }
 
 impl BSPNode {
-    /// Build a BSP tree from polygons
-    fn build(mut polygons: Vec<Polygon>) -> Option<Box<BSPNode>> {
-        if polygons.is_empty() {
-            return None;
-        }
-
-        // Choose first polygon's plane as splitting plane
-        let plane = polygons[0].plane;
+    /// Create a node split by the first polygon's plane
+    fn new(polygons: Vec<Polygon>) -> Option<Box<BSPNode>> {
+        let plane = polygons.first()?.plane;
         let mut node = Box::new(BSPNode {
             plane,
             front: None,
             back: None,
             polygons: Vec::new(),
         });
+        node.build(polygons);
+        Some(node)
+    }
 
+    /// Add polygons below this node
+    fn build(&mut self, polygons: Vec<Polygon>) {
+        let mut coplanar = Vec::new();
         let mut front_polygons = Vec::new();
         let mut back_polygons = Vec::new();
 
-        for polygon in polygons.drain(..) {
-            node.split_polygon(polygon, &mut node.polygons, &mut front_polygons, &mut back_polygons);
+        for polygon in polygons {
+            // Coplanar polygons live on this node whichever way they face
+            let mut coplanar_back = Vec::new();
+            self.split_polygon(polygon, &mut coplanar, &mut coplanar_back, &mut front_polygons, &mut back_polygons);
+            coplanar.append(&mut coplanar_back);
         }
+        self.polygons.append(&mut coplanar);
 
-        node.front = Self::build(front_polygons);
-        node.back = Self::build(back_polygons);
+        if !front_polygons.is_empty() {
+            match self.front {
+                Some(ref mut front) => front.build(front_polygons),
+                None => self.front = Self::new(front_polygons),
+            }
+        }
 
-        Some(node)
+        if !back_polygons.is_empty() {
+            match self.back {
+                Some(ref mut back) => back.build(back_polygons),
+                None => self.back = Self::new(back_polygons),
+            }
+        }
     }
 
     /// Split a polygon by this node's plane
+    ///
+    /// Coplanar polygons are sorted by whether they face the same way as the
+    /// plane, which is what lets coincident faces of two solids cancel or
+    /// merge instead of both surviving.
     fn split_polygon(
         &self,
         polygon: Polygon,
-        coplanar: &mut Vec<Polygon>,
+        coplanar_front: &mut Vec<Polygon>,
+        coplanar_back: &mut Vec<Polygon>,
         front: &mut Vec<Polygon>,
         back: &mut Vec<Polygon>,
     ) {
-        // Classify all vertices
-        let mut classifications = Vec::new();
         let mut has_front = false;
         let mut has_back = false;
 
         for vertex in &polygon.vertices {
-            let class = self.plane.classify_point(vertex);
-            if class == PointClassification::Front {
-                has_front = true;
-            } else if class == PointClassification::Back {
-                has_back = true;
+            match self.plane.classify_point(vertex) {
+                PointClassification::Front => has_front = true,
+                PointClassification::Back => has_back = true,
+                PointClassification::OnPlane => {}
             }
-            classifications.push(class);
         }
 
         if !has_front && !has_back {
-            // All vertices are on plane
-            coplanar.push(polygon);
+            if self.plane.normal.dot(&polygon.plane.normal) > 0.0 {
+                coplanar_front.push(polygon);
+            } else {
+                coplanar_back.push(polygon);
+            }
         } else if !has_back {
-            // All vertices in front
             front.push(polygon);
         } else if !has_front {
-            // All vertices in back
             back.push(polygon);
         } else {
             // Polygon spans plane - split it
@@ -306,29 +378,24 @@ impl BSPNode {
 
     /// Clip polygons to this node
     fn clip_polygons(&self, polygons: Vec<Polygon>) -> Vec<Polygon> {
-        let mut result = Vec::new();
+        let mut front = Vec::new();
+        let mut back = Vec::new();
 
         for polygon in polygons {
-            let mut coplanar = Vec::new();
-            let mut front = Vec::new();
-            let mut back = Vec::new();
-
-            self.split_polygon(polygon, &mut coplanar, &mut front, &mut back);
-
-            // Coplanar polygons go to front
-            result.extend(coplanar);
+            let (mut coplanar_front, mut coplanar_back) = (Vec::new(), Vec::new());
+            self.split_polygon(polygon, &mut coplanar_front, &mut coplanar_back, &mut front, &mut back);
+            front.append(&mut coplanar_front);
+            back.append(&mut coplanar_back);
+        }
 
-            // Recursively clip front and back
-            if let Some(ref front_node) = self.front {
-                result.extend(front_node.clip_polygons(front));
-            } else {
-                result.extend(front);
-            }
+        let mut result = match self.front {
+            Some(ref front_node) => front_node.clip_polygons(front),
+            None => front,
+        };
 
-            if let Some(ref back_node) = self.back {
-                result.extend(back_node.clip_polygons(back));
-            }
-            // Back polygons that don't have a back node are clipped away
+        // Back polygons that don't have a back node are inside the solid
+        if let Some(ref back_node) = self.back {
+            result.extend(back_node.clip_polygons(back));
         }
 
         result
@@ -389,32 +456,126 @@ pub enum BooleanOp {
     Difference,
 }
 
-/// Perform a CSG boolean operation on two meshes
+impl BooleanOp {
+    /// Range the result volume must fall in, given the operand volumes
+    fn volume_bounds(&self, volume_a: f64, volume_b: f64) -> (f64, f64) {
+        match self {
+            BooleanOp::Union => (volume_a.max(volume_b), volume_a + volume_b),
+            BooleanOp::Intersection => (0.0, volume_a.min(volume_b)),
+            BooleanOp::Difference => ((volume_a - volume_b).max(0.0), volume_a),
+        }
+    }
+}
+
+/// Options for [`boolean_operation_with`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BooleanOptions {
+    /// Reject operands that are not closed, consistently oriented solids
+    pub check_operands: bool,
+    /// Reject results that are not closed solids or whose volume is
+    /// impossible for the operation
+    pub check_result: bool,
+}
+
+impl Default for BooleanOptions {
+    fn default() -> Self {
+        Self {
+            check_operands: true,
+            check_result: true,
+        }
+    }
+}
+
+/// Outcome of a boolean operation
+#[derive(Debug, Clone)]
+pub struct BooleanResult {
+    pub mesh: HalfEdgeMesh,
+    pub validity: MeshValidity,
+}
+
+/// Perform a CSG boolean operation on two closed meshes
+///
+/// Operands and result are checked with the default [`BooleanOptions`].
 pub fn boolean_operation(
     mesh_a: &HalfEdgeMesh,
     mesh_b: &HalfEdgeMesh,
     operation: BooleanOp,
-) -> Result<HalfEdgeMesh, MeshError> {
-    // Convert meshes to polygons
-    let mut polygons_a = mesh_to_polygons(mesh_a)?;
-    let mut polygons_b = mesh_to_polygons(mesh_b)?;
+) -> Result<HalfEdgeMesh, BooleanError> {
+    boolean_operation_with(mesh_a, mesh_b, operation, &BooleanOptions::default()).map(|result| result.mesh)
+}
+
+/// Perform a CSG boolean operation with explicit checking options
+///
+/// Inside-out operands (negative volume) are flipped before combining.
+pub fn boolean_operation_with(
+    mesh_a: &HalfEdgeMesh,
+    mesh_b: &HalfEdgeMesh,
+    operation: BooleanOp,
+    options: &BooleanOptions,
+) -> Result<BooleanResult, BooleanError> {
+    let validity_a = MeshValidity::check(mesh_a)?;
+    let validity_b = MeshValidity::check(mesh_b)?;
+    if options.check_operands {
+        for (operand, validity) in [("A", &validity_a), ("B", &validity_b)] {
+            if validity.faces > 0 && !validity.is_closed() {
+                return Err(BooleanError::OpenOperand {
+                    operand,
+                    validity: validity.clone(),
+                });
+            }
+        }
+    }
+
+    let polygons_a = operand_polygons(mesh_a, &validity_a)?;
+    let polygons_b = operand_polygons(mesh_b, &validity_b)?;
+
+    let result_polygons = if polygons_a.is_empty() || polygons_b.is_empty() {
+        // An empty tree clips nothing, so empty operands are settled directly
+        match operation {
+            BooleanOp::Union if polygons_a.is_empty() => polygons_b,
+            BooleanOp::Union | BooleanOp::Difference => polygons_a,
+            BooleanOp::Intersection => Vec::new(),
+        }
+    } else {
+        combine(polygons_a, polygons_b, operation)
+    };
+
+    let mesh = polygons_to_mesh(&result_polygons)?;
+    let validity = MeshValidity::check(&mesh)?;
+
+    if options.check_result {
+        if !validity.is_valid() {
+            return Err(BooleanError::InvalidResult(validity));
+        }
+
+        let (min, max) = operation.volume_bounds(validity_a.volume.abs(), validity_b.volume.abs());
+        let tolerance = 1e-6 * (validity_a.volume.abs() + validity_b.volume.abs()) + PLANE_EPSILON;
+        if validity.volume < min - tolerance || validity.volume > max + tolerance {
+            return Err(BooleanError::VolumeOutOfRange {
+                volume: validity.volume,
+                min,
+                max,
+            });
+        }
+    }
 
-    // Build BSP trees
+    Ok(BooleanResult { mesh, validity })
+}
+
+/// Run the BSP clipping sequence for an operation
+fn combine(polygons_a: Vec<Polygon>, polygons_b: Vec<Polygon>, operation: BooleanOp) -> Vec<Polygon> {
     let mut tree_a = BSPTree::from_polygons(polygons_a);
     let mut tree_b = BSPTree::from_polygons(polygons_b);
 
-    // Perform operation
     match operation {
         BooleanOp::Union => {
             tree_a.clip_to(&tree_b);
             tree_b.clip_to(&tree_a);
+            // Drop B's copies of faces coincident with A's
             tree_b.invert();
             tree_b.clip_to(&tree_a);
             tree_b.invert();
-
-            let mut result_polygons = tree_a.all_polygons();
-            result_polygons.extend(tree_b.all_polygons());
-            polygons_to_mesh(&result_polygons)
+            tree_a.build(tree_b.all_polygons());
         }
         BooleanOp::Intersection => {
             tree_a.invert();
@@ -422,11 +583,8 @@ pub fn boolean_operation(
             tree_b.invert();
             tree_a.clip_to(&tree_b);
             tree_b.clip_to(&tree_a);
+            tree_a.build(tree_b.all_polygons());
             tree_a.invert();
-
-            let mut result_polygons = tree_a.all_polygons();
-            result_polygons.extend(tree_b.all_polygons());
-            polygons_to_mesh(&result_polygons)
         }
         BooleanOp::Difference => {
             tree_a.invert();
@@ -435,13 +593,21 @@ pub fn boolean_operation(
             tree_b.invert();
             tree_b.clip_to(&tree_a);
             tree_b.invert();
+            tree_a.build(tree_b.all_polygons());
             tree_a.invert();
-
-            let mut result_polygons = tree_a.all_polygons();
-            result_polygons.extend(tree_b.all_polygons());
-            polygons_to_mesh(&result_polygons)
         }
     }
+
+    tree_a.all_polygons()
+}
+
+/// Polygons of an operand, turned outward if the mesh is inside out
+fn operand_polygons(mesh: &HalfEdgeMesh, validity: &MeshValidity) -> Result<Vec<Polygon>, MeshError> {
+    let mut polygons = mesh_to_polygons(mesh)?;
+    if validity.volume < 0.0 {
+        polygons.iter_mut().for_each(Polygon::flip);
+    }
+    Ok(polygons)
 }
 
 /// Convert a mesh to polygons
@@ -467,21 +633,64 @@ fn mesh_to_polygons(mesh: &HalfEdgeMesh) -> Result<Vec<Polygon>, MeshError> {
 }
 
 /// Convert polygons back to a mesh
+///
+/// Vertices are welded, and vertices that landed on a neighbour's edge when
+/// it was split elsewhere (T-junctions) are inserted into that edge so the
+/// faces share edges again.
 fn polygons_to_mesh(polygons: &[Polygon]) -> Result<HalfEdgeMesh, MeshError> {
-    let mut mesh = HalfEdgeMesh::new();
-    let mut vertex_map: HashMap<VertexKey, VertexHandle> = HashMap::new();
+    let mut points: Vec<Point3> = Vec::new();
+    let mut vertex_map: HashMap<VertexKey, usize> = HashMap::new();
+    let mut loops: Vec<Vec<usize>> = Vec::with_capacity(polygons.len());
 
     for polygon in polygons {
-        let mut vertex_handles = Vec::new();
-
+        let mut indices: Vec<usize> = Vec::with_capacity(polygon.vertices.len());
         for point in &polygon.vertices {
-            let key = VertexKey::from_point(point);
-            let vh = vertex_map.entry(key).or_insert_with(|| {
-                mesh.add_vertex(*point)
+            let index = *vertex_map.entry(VertexKey::from_point(point)).or_insert_with(|| {
+                points.push(*point);
+                points.len() - 1
             });
-            vertex_handles.push(*vh);
+            if indices.last() != Some(&index) {
+                indices.push(index);
+            }
+        }
+        while indices.len() > 1 && indices.first() == indices.last() {
+            indices.pop();
+        }
+        loops.push(indices);
+    }
+
+    // Vertices sorted by x so each edge only scans the ones in its extent
+    let mut by_x: Vec<usize> = (0..points.len()).collect();
+    by_x.sort_by(|&a, &b| points[a].x.total_cmp(&points[b].x));
+
+    let mut mesh = HalfEdgeMesh::new();
+    let handles: Vec<VertexHandle> = points.iter().map(|p| mesh.add_vertex(*p)).collect();
+
+    for indices in loops {
+        let mut outline = Vec::with_capacity(indices.len());
+        for (i, &a) in indices.iter().enumerate() {
+            outline.push(a);
+            let b = indices[(i + 1) % indices.len()];
+            outline.extend(vertices_on_edge(&points, &by_x, a, b));
+        }
+
+        if outline.len() < 3 || has_repeats(&outline) {
+            continue;
+        }
+
+        // Start from a corner so the mesh's face normal isn't taken from a
+        // collinear run
+        if let Some(corner) = (0..outline.len()).find(|&i| {
+            let n = outline.len();
+            let (p0, p1, p2) = (points[outline[i]], points[outline[(i + 1) % n]], points[outline[(i + 2) % n]]);
+            (p1 - p0).cross(&(p2 - p0)).norm() > PLANE_EPSILON * PLANE_EPSILON
+        }) {
+            outline.rotate_left(corner);
+        } else {
+            continue;
         }
 
+        let vertex_handles: Vec<VertexHandle> = outline.iter().map(|&i| handles[i]).collect();
         mesh.add_face(&vertex_handles)?;
     }
 
@@ -490,6 +699,38 @@ fn polygons_to_mesh(polygons: &[Polygon]) -> Result<HalfEdgeMesh, MeshError> {
     Ok(mesh)
 }
 
+/// Welded vertices lying strictly inside edge `a`-`b`, ordered from `a`
+fn vertices_on_edge(points: &[Point3], by_x: &[usize], a: usize, b: usize) -> Vec<usize> {
+    let (pa, pb) = (points[a], points[b]);
+    let edge = pb - pa;
+    let length_sq = edge.norm_squared();
+    if length_sq < PLANE_EPSILON * PLANE_EPSILON {
+        return Vec::new();
+    }
+
+    let (min_x, max_x) = (pa.x.min(pb.x) - PLANE_EPSILON, pa.x.max(pb.x) + PLANE_EPSILON);
+    let start = by_x.partition_point(|&i| points[i].x < min_x);
+
+    let mut hits: Vec<(f64, usize)> = by_x[start..]
+        .iter()
+        .take_while(|&&i| points[i].x <= max_x)
+        .filter(|&&i| i != a && i != b)
+        .filter_map(|&i| {
+            let t = (points[i] - pa).dot(&edge) / length_sq;
+            let off = (pa + edge * t - points[i]).norm();
+            (t > 0.0 && t < 1.0 && off < PLANE_EPSILON).then_some((t, i))
+        })
+        .collect();
+    hits.sort_by(|x, y| x.0.total_cmp(&y.0));
+    hits.into_iter().map(|(_, i)| i).collect()
+}
+
+fn has_repeats(indices: &[usize]) -> bool {
+    let mut sorted = indices.to_vec();
+    sorted.sort_unstable();
+    sorted.windows(2).any(|w| w[0] == w[1])
+}
+
 /// Key for vertex deduplication
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct VertexKey {
@@ -509,10 +750,133 @@ impl VertexKey {
     }
 }
 
+/// Solid validity of a mesh
+///
+/// Edges are matched by vertex position rather than handle, so meshes built
+/// with duplicated vertices along seams are still recognised as closed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MeshValidity {
+    /// Number of faces checked
+    pub faces: usize,
+    /// Edges used by only one face
+    pub boundary_edges: usize,
+    /// Edges shared by more than two faces
+    pub non_manifold_edges: usize,
+    /// Edges whose two faces run the same way, i.e. disagree on orientation
+    pub inconsistent_edges: usize,
+    /// Faces with (near) zero area
+    pub degenerate_faces: usize,
+    /// Enclosed volume; negative when the mesh is inside out
+    pub volume: f64,
+}
+
+impl MeshValidity {
+    /// Check a mesh
+    pub fn check(mesh: &HalfEdgeMesh) -> Result<Self, MeshError> {
+        let mut validity = Self::default();
+        // (forward, backward) uses per undirected edge
+        let mut edges: HashMap<(VertexKey, VertexKey), (usize, usize)> = HashMap::new();
+
+        for face_handle in mesh.face_handles() {
+            let mut points = Vec::new();
+            for vh in mesh.face_vertices(face_handle)? {
+                points.push(mesh.get_vertex(vh)?.position);
+            }
+            validity.faces += 1;
+
+            if newell_normal(&points).norm() * 0.5 < PLANE_EPSILON * PLANE_EPSILON {
+                validity.degenerate_faces += 1;
+            }
+
+            for i in 1..points.len().saturating_sub(1) {
+                validity.volume += points[0].coords.dot(&points[i].coords.cross(&points[i + 1].coords)) / 6.0;
+            }
+
+            for (i, a) in points.iter().enumerate() {
+                let (ka, kb) = (VertexKey::from_point(a), VertexKey::from_point(&points[(i + 1) % points.len()]));
+                let key = if (ka.x, ka.y, ka.z) <= (kb.x, kb.y, kb.z) { (ka, kb) } else { (kb, ka) };
+                let uses = edges.entry(key).or_default();
+                if key.0 == ka {
+                    uses.0 += 1;
+                } else {
+                    uses.1 += 1;
+                }
+            }
+        }
+
+        for (forward, backward) in edges.into_values() {
+            match forward + backward {
+                1 => validity.boundary_edges += 1,
+                2 if forward != 1 => validity.inconsistent_edges += 1,
+                2 => {}
+                _ => validity.non_manifold_edges += 1,
+            }
+        }
+
+        Ok(validity)
+    }
+
+    /// Whether every edge joins exactly two consistently oriented faces
+    pub fn is_closed(&self) -> bool {
+        self.boundary_edges == 0 && self.non_manifold_edges == 0 && self.inconsistent_edges == 0
+    }
+
+    /// Whether the mesh is a closed, outward-facing solid without degenerate
+    /// faces. An empty mesh is valid (the empty solid).
+    pub fn is_valid(&self) -> bool {
+        self.is_closed() && self.degenerate_faces == 0 && self.volume >= -PLANE_EPSILON
+    }
+}
+
+/// Boolean operation errors
+#[derive(Debug, thiserror::Error)]
+pub enum BooleanError {
+    #[error(
+        "Operand {operand} is not a closed solid ({} boundary, {} non-manifold, {} inconsistent edges)",
+        validity.boundary_edges, validity.non_manifold_edges, validity.inconsistent_edges
+    )]
+    OpenOperand { operand: &'static str, validity: MeshValidity },
+
+    #[error(
+        "Boolean result is not a valid solid ({} boundary, {} non-manifold, {} inconsistent edges, {} degenerate faces)",
+        .0.boundary_edges, .0.non_manifold_edges, .0.inconsistent_edges, .0.degenerate_faces
+    )]
+    InvalidResult(MeshValidity),
+
+    #[error("Boolean result volume {volume} is outside the possible range {min}..{max}")]
+    VolumeOutOfRange { volume: f64, min: f64, max: f64 },
+
+    #[error(transparent)]
+    Mesh(#[from] MeshError),
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Outward-facing axis-aligned box
+    fn cube(min: [f64; 3], max: [f64; 3]) -> HalfEdgeMesh {
+        let mut mesh = HalfEdgeMesh::new();
+        let v: Vec<VertexHandle> = (0..8)
+            .map(|i| {
+                let x = if i & 1 == 0 { min[0] } else { max[0] };
+                let y = if i & 2 == 0 { min[1] } else { max[1] };
+                let z = if i & 4 == 0 { min[2] } else { max[2] };
+                mesh.add_vertex(Point3::new(x, y, z))
+            })
+            .collect();
+        for face in [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]] {
+            mesh.add_face(&face.map(|i| v[i])).unwrap();
+        }
+        mesh
+    }
+
+    fn volume(mesh: &HalfEdgeMesh) -> f64 {
+        let validity = MeshValidity::check(mesh).unwrap();
+        assert!(validity.is_valid(), "{:?}", validity);
+        validity.volume
+    }
+
     #[test]
     fn test_plane_creation() {
         let p1 = Point3::new(0.0, 0.0, 0.0);
@@ -551,5 +915,77 @@ mod tests {
 
         let polygon = Polygon::new(vertices).unwrap();
         assert_eq!(polygon.vertices.len(), 3);
+
+        // Leading collinear vertices still give the right plane
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.5, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ])
+        .unwrap();
+        assert!((polygon.plane.normal.z - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_overlapping_cubes() {
+        let a = cube([0.0; 3], [1.0; 3]);
+        let b = cube([0.5; 3], [1.5; 3]);
+
+        let union = boolean_operation(&a, &b, BooleanOp::Union).unwrap();
+        assert!((volume(&union) - 1.875).abs() < 1e-9);
+
+        let difference = boolean_operation(&a, &b, BooleanOp::Difference).unwrap();
+        assert!((volume(&difference) - 0.875).abs() < 1e-9);
+
+        let intersection = boolean_operation(&a, &b, BooleanOp::Intersection).unwrap();
+        assert!((volume(&intersection) - 0.125).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_shared_faces() {
+        // Touching face to face: the shared wall disappears from the union
+        let a = cube([0.0; 3], [1.0; 3]);
+        let b = cube([1.0, 0.0, 0.0], [2.0, 1.0, 1.0]);
+        let union = boolean_operation(&a, &b, BooleanOp::Union).unwrap();
+        assert!((volume(&union) - 2.0).abs() < 1e-9);
+        for face in union.face_handles() {
+            let xs: Vec<f64> = union
+                .face_vertices(face)
+                .unwrap()
+                .into_iter()
+                .map(|v| union.get_vertex(v).unwrap().position.x)
+                .collect();
+            assert!(xs.iter().any(|x| (x - 1.0).abs() > 1e-9), "internal wall left at x = 1");
+        }
+        let difference = boolean_operation(&a, &b, BooleanOp::Difference).unwrap();
+        assert!((volume(&difference) - 1.0).abs() < 1e-9);
+
+        // Overlapping with coplanar top, bottom and sides
+        let c = cube([0.5, 0.0, 0.0], [1.5, 1.0, 1.0]);
+        let union = boolean_operation(&a, &c, BooleanOp::Union).unwrap();
+        assert!((volume(&union) - 1.5).abs() < 1e-9);
+        let intersection = boolean_operation(&a, &c, BooleanOp::Intersection).unwrap();
+        assert!((volume(&intersection) - 0.5).abs() < 1e-9);
+        let difference = boolean_operation(&a, &c, BooleanOp::Difference).unwrap();
+        assert!((volume(&difference) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_operand_checks() {
+        let a = cube([0.0; 3], [1.0; 3]);
+        let mut open = cube([0.5; 3], [1.5; 3]);
+        open.delete_face(open.face_handles()[0]).unwrap();
+        assert!(matches!(
+            boolean_operation(&a, &open, BooleanOp::Union),
+            Err(BooleanError::OpenOperand { operand: "B", .. })
+        ));
+
+        // Disjoint intersection and empty operands give the empty solid
+        let far = cube([5.0; 3], [6.0; 3]);
+        let empty = boolean_operation(&a, &far, BooleanOp::Intersection).unwrap();
+        assert_eq!(empty.stats().faces, 0);
+        let same = boolean_operation(&a, &HalfEdgeMesh::new(), BooleanOp::Difference).unwrap();
+        assert!((volume(&same) - 1.0).abs() < 1e-9);
     }
 }
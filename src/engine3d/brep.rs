@@ -0,0 +1,1445 @@
+//! Boundary representation solids
+//!
+//! A [`Brep`] keeps the topology of a solid explicitly: shells own faces,
+//! each face is bounded by an outer loop and any number of inner rings,
+//! loops are cycles of coedges, and every edge is used by exactly two
+//! coedges running in opposite directions. Faces and edges carry their
+//! exact geometry (planes, analytic and NURBS surfaces; lines, arcs and
+//! NURBS curves), so booleans, fillets and STEP exchange can work on the
+//! true shape instead of its facets.
+//!
+//! Topology only changes through Euler operators, each of which keeps the
+//! Euler–Poincaré formula V − E + F − R = 2(S − H) true:
+//!
+//! | Operator | Makes | Inverse |
+//! |---|---|---|
+//! | [`mvfs`](Brep::mvfs) | vertex, face and shell | [`kvfs`](Brep::kvfs) |
+//! | [`mev`](Brep::mev) | edge and vertex | [`kev`](Brep::kev) |
+//! | [`mef`](Brep::mef) | edge and face | [`kef`](Brep::kef) |
+//! | [`mekr`](Brep::mekr) | edge, killing a ring | [`kemr`](Brep::kemr) |
+//! | [`kfmrh`](Brep::kfmrh) | ring and hole, killing a face | [`mfkrh`](Brep::mfkrh) |
+//!
+//! Every loop keeps its face on the left seen from outside the solid, so
+//! outer loops run counter-clockwise and rings clockwise about the outward
+//! normal, whichever way the face's surface is parameterized.
+
+use super::intersection::{AnalyticSurface, Frame};
+use super::mesh::{HalfEdgeMesh, MeshError, VertexHandle};
+use super::nurbs::{NurbsCurve, NurbsSurface};
+use super::tessellation::{AdaptiveTessellator, TessellationError, TessellationSettings};
+use super::topology::{newell_normal, wound, ProfileRegion};
+use crate::core::{Point3, Vector3, EPSILON};
+use crate::geometry::point::Point2D;
+use crate::geometry::tessellate::tessellate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::{PI, TAU};
+
+/// Handle types for B-rep elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct VertexId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct EdgeId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CoedgeId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct LoopId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct FaceId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ShellId(pub usize);
+
+/// Exact curve of an edge, running from its start vertex to its end vertex
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum EdgeCurve {
+    /// Straight segment
+    #[default]
+    Line,
+    /// Circular arc counter-clockwise about `axis` around `center`; a full
+    /// circle when the edge starts and ends at the same vertex
+    Arc { center: Point3, axis: Vector3 },
+    /// Free-form curve over its whole parameter range
+    Nurbs(NurbsCurve),
+}
+
+impl EdgeCurve {
+    /// The same curve traversed the other way
+    pub fn reversed(&self) -> Self {
+        match self {
+            EdgeCurve::Line => EdgeCurve::Line,
+            EdgeCurve::Arc { center, axis } => EdgeCurve::Arc {
+                center: *center,
+                axis: -axis,
+            },
+            EdgeCurve::Nurbs(curve) => {
+                let (first, last) = (curve.knots[0], curve.knots[curve.knots.len() - 1]);
+                EdgeCurve::Nurbs(NurbsCurve {
+                    degree: curve.degree,
+                    control_points: curve.control_points.iter().rev().copied().collect(),
+                    knots: curve.knots.iter().rev().map(|k| first + last - k).collect(),
+                })
+            }
+        }
+    }
+
+    /// The curve moved by `offset`
+    pub fn translated(&self, offset: &Vector3) -> Self {
+        match self {
+            EdgeCurve::Line => EdgeCurve::Line,
+            EdgeCurve::Arc { center, axis } => EdgeCurve::Arc {
+                center: center + offset,
+                axis: *axis,
+            },
+            EdgeCurve::Nurbs(curve) => EdgeCurve::Nurbs(NurbsCurve {
+                control_points: curve
+                    .control_points
+                    .iter()
+                    .map(|p| translate_homogeneous(p, offset))
+                    .collect(),
+                ..curve.clone()
+            }),
+        }
+    }
+}
+
+/// Exact surface of a face
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum FaceSurface {
+    /// Unbounded plane; `normal` points out of the solid
+    Plane { origin: Point3, normal: Vector3 },
+    /// Cylinder, cone, sphere or torus
+    Analytic(AnalyticSurface),
+    /// Free-form surface
+    Nurbs(NurbsSurface),
+}
+
+impl FaceSurface {
+    /// The surface moved by `offset`
+    pub fn translated(&self, offset: &Vector3) -> Self {
+        match self {
+            FaceSurface::Plane { origin, normal } => FaceSurface::Plane {
+                origin: origin + offset,
+                normal: *normal,
+            },
+            FaceSurface::Analytic(surface) => {
+                let mut surface = surface.clone();
+                match &mut surface {
+                    AnalyticSurface::Plane { frame, .. }
+                    | AnalyticSurface::Cylinder { frame, .. }
+                    | AnalyticSurface::Cone { frame, .. }
+                    | AnalyticSurface::Sphere { frame, .. }
+                    | AnalyticSurface::Torus { frame, .. } => frame.origin += offset,
+                }
+                FaceSurface::Analytic(surface)
+            }
+            FaceSurface::Nurbs(surface) => FaceSurface::Nurbs(NurbsSurface {
+                control_points: surface
+                    .control_points
+                    .iter()
+                    .map(|row| row.iter().map(|p| translate_homogeneous(p, offset)).collect())
+                    .collect(),
+                ..surface.clone()
+            }),
+        }
+    }
+}
+
+/// B-rep vertex
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrepVertex {
+    /// Position
+    pub point: Point3,
+}
+
+/// B-rep edge, used by two coedges
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrepEdge {
+    /// Exact curve, from the vertex of the first coedge to that of the second
+    pub curve: EdgeCurve,
+    /// The coedge running along the curve, then the one running against it
+    pub coedges: [CoedgeId; 2],
+}
+
+/// Use of an edge by a loop
+///
+/// A loop around a lone vertex holds a single coedge with no edge.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Coedge {
+    /// Loop the coedge belongs to
+    pub loop_id: LoopId,
+    /// Edge used, `None` for a lone vertex
+    pub edge: Option<EdgeId>,
+    /// Vertex the coedge starts at
+    pub vertex: VertexId,
+    /// Next coedge in the loop
+    pub next: CoedgeId,
+    /// Previous coedge in the loop
+    pub prev: CoedgeId,
+}
+
+/// Closed cycle of coedges bounding a face
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrepLoop {
+    /// Face the loop bounds
+    pub face: FaceId,
+    /// Any coedge of the loop
+    pub first: CoedgeId,
+}
+
+/// B-rep face
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrepFace {
+    /// Shell the face belongs to
+    pub shell: ShellId,
+    /// Outer boundary
+    pub outer: LoopId,
+    /// Inner boundaries
+    pub rings: Vec<LoopId>,
+    /// Exact surface, if known
+    pub surface: Option<FaceSurface>,
+}
+
+/// Connected set of faces
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BrepShell {
+    /// Faces of the shell
+    pub faces: Vec<FaceId>,
+}
+
+/// Boundary representation of one or more solids
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Brep {
+    vertices: Vec<Option<BrepVertex>>,
+    edges: Vec<Option<BrepEdge>>,
+    coedges: Vec<Option<Coedge>>,
+    loops: Vec<Option<BrepLoop>>,
+    faces: Vec<Option<BrepFace>>,
+    shells: Vec<Option<BrepShell>>,
+}
+
+impl Brep {
+    /// Create an empty B-rep
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // ------------------------------------------------------------------
+    // Builders
+    // ------------------------------------------------------------------
+
+    /// Solid swept from a planar region along `direction`
+    ///
+    /// Caps and side walls are planes and every edge a line; the direction
+    /// may be oblique to the region but not parallel to it.
+    pub fn extrude(region: &ProfileRegion, direction: Vector3) -> BrepResult<Self> {
+        let normal = region.normal().ok_or(BrepError::DegenerateProfile)?;
+        let normal = if normal.dot(&direction) < 0.0 { -normal } else { normal };
+        if normal.dot(&direction) < EPSILON {
+            return Err(BrepError::DegenerateProfile);
+        }
+        let outer = wound(&region.outer, &normal, true);
+        let holes: Vec<_> = region.holes.iter().map(|hole| wound(hole, &normal, false)).collect();
+        if std::iter::once(&outer).chain(&holes).any(|ring| ring.len() < 3) {
+            return Err(BrepError::DegenerateProfile);
+        }
+
+        let mut brep = Self::new();
+        let (top, bottom) = brep.lamina(&outer)?;
+        brep.face_mut(top)?.surface = Some(FaceSurface::Plane {
+            origin: outer[0],
+            normal,
+        });
+        brep.face_mut(bottom)?.surface = Some(FaceSurface::Plane {
+            origin: outer[0],
+            normal: -normal,
+        });
+        for hole in &holes {
+            // Hang the hole off the outer loop by a strut, close it into a
+            // cap face, cut the strut to make it a ring of the top, then
+            // punch the cap through the bottom
+            let anchor = brep.face_loop(brep.face(top)?.outer)?.first;
+            let (strut, _) = brep.mev(anchor, hole[0])?;
+            let [down, up] = brep.edge(strut)?.coedges;
+            let tip = brep.chain(up, &hole[1..])?;
+            let (_, cap) = brep.mef(tip, up)?;
+            brep.kemr(down)?;
+            brep.kfmrh(cap, bottom)?;
+        }
+        brep.sweep(top, direction)?;
+        Ok(brep)
+    }
+
+    /// Solid cylinder standing on `base` along `axis`
+    ///
+    /// Each cap is bounded by two half-circle arcs and the wall is split
+    /// into two half cylinders along straight seams.
+    pub fn cylinder(base: Point3, axis: Vector3, radius: f64, height: f64) -> BrepResult<Self> {
+        if radius < EPSILON || height < EPSILON || axis.norm() < EPSILON {
+            return Err(BrepError::DegenerateProfile);
+        }
+        let frame = Frame::new(base, axis);
+        let mut brep = Self::new();
+        let (_, top, _) = brep.mvfs(base + frame.x_axis * radius);
+        let lone = brep.face_loop(brep.face(top)?.outer)?.first;
+        let (front, _) = brep.mev(lone, base - frame.x_axis * radius)?;
+        let [out, back] = brep.edge(front)?.coedges;
+        let (rear, bottom) = brep.mef(back, out)?;
+        for edge in [front, rear] {
+            brep.edge_mut(edge)?.curve = EdgeCurve::Arc {
+                center: base,
+                axis: frame.z_axis,
+            };
+        }
+        brep.face_mut(top)?.surface = Some(FaceSurface::Plane {
+            origin: base,
+            normal: frame.z_axis,
+        });
+        brep.face_mut(bottom)?.surface = Some(FaceSurface::Plane {
+            origin: base,
+            normal: -frame.z_axis,
+        });
+        brep.sweep(top, frame.z_axis * height)?;
+        Ok(brep)
+    }
+
+    /// B-rep with one planar face per mesh face
+    ///
+    /// Built directly rather than through Euler operators; the mesh must be
+    /// closed, with each edge shared by two faces in opposite directions.
+    /// Face `i` comes from the `i`th of the mesh's face handles.
+    pub fn from_mesh(mesh: &HalfEdgeMesh) -> BrepResult<Self> {
+        let mut brep = Self::new();
+        let shell = ShellId(brep.shells.len());
+        brep.shells.push(Some(BrepShell::default()));
+        let mut vertices = HashMap::new();
+        for vh in mesh.vertex_handles() {
+            let point = mesh.get_vertex(vh)?.position;
+            vertices.insert(vh, brep.add_vertex(point));
+        }
+
+        let mut coedges = HashMap::new();
+        for fh in mesh.face_handles() {
+            let corners = mesh.face_vertices(fh)?;
+            let mut points = Vec::with_capacity(corners.len());
+            for vh in &corners {
+                points.push(mesh.get_vertex(*vh)?.position);
+            }
+            let normal = newell_normal(&points)
+                .try_normalize(EPSILON)
+                .ok_or_else(|| BrepError::Inconsistent(format!("face {} is degenerate", fh.0)))?;
+            let face = brep.add_face(shell, Some(FaceSurface::Plane { origin: points[0], normal }));
+            let loop_id = brep.face(face)?.outer;
+            let cycle: Vec<_> = corners
+                .iter()
+                .map(|vh| brep.add_coedge(loop_id, None, vertices[vh]))
+                .collect();
+            brep.close_loop(loop_id, &cycle, vertices[&corners[0]])?;
+            for (k, &coedge) in cycle.iter().enumerate() {
+                coedges.insert((corners[k], corners[(k + 1) % corners.len()]), coedge);
+            }
+        }
+
+        for (&(a, b), &coedge) in &coedges {
+            if brep.coedge(coedge)?.edge.is_some() {
+                continue;
+            }
+            let mate = *coedges
+                .get(&(b, a))
+                .ok_or_else(|| BrepError::Inconsistent(format!("edge {}-{} has one face", a.0, b.0)))?;
+            let edge = EdgeId(brep.edges.len());
+            brep.edges.push(Some(BrepEdge {
+                curve: EdgeCurve::Line,
+                coedges: [coedge, mate],
+            }));
+            brep.coedge_mut(coedge)?.edge = Some(edge);
+            brep.coedge_mut(mate)?.edge = Some(edge);
+        }
+        Ok(brep)
+    }
+
+    /// Two-sided face bounded by `points`, returning the face the points
+    /// wind around counter-clockwise and the face on its back
+    fn lamina(&mut self, points: &[Point3]) -> BrepResult<(FaceId, FaceId)> {
+        let (_, front, first) = self.mvfs(points[0]);
+        let loop_id = self.face(front)?.outer;
+        let tip = self.chain(self.face_loop(loop_id)?.first, &points[1..])?;
+        let start = self.coedge_from(loop_id, first)?;
+        let (_, back) = self.mef(tip, start)?;
+        Ok((front, back))
+    }
+
+    /// Grow a chain of struts through `points` from the start of `at`,
+    /// returning the coedge leading back from the last point
+    fn chain(&mut self, mut at: CoedgeId, points: &[Point3]) -> BrepResult<CoedgeId> {
+        for point in points {
+            let (edge, _) = self.mev(at, *point)?;
+            at = self.edge(edge)?.coedges[1];
+        }
+        Ok(at)
+    }
+
+    /// Sweep a face along `direction`, turning each of its edges into a
+    /// side face and moving the face to the far end
+    ///
+    /// Side faces get planes, cylinders or ruled NURBS surfaces as their
+    /// edges' curves allow. Every loop of the face needs at least two
+    /// edges.
+    pub fn sweep(&mut self, face: FaceId, direction: Vector3) -> BrepResult<()> {
+        let loops: Vec<_> = {
+            let face = self.face(face)?;
+            std::iter::once(face.outer).chain(face.rings.iter().copied()).collect()
+        };
+        for loop_id in loops {
+            let base = self.cycle(self.face_loop(loop_id)?.first)?;
+            if base.len() < 2 {
+                return Err(BrepError::Precondition("swept loops need two edges"));
+            }
+            // Stand a strut on every vertex, then close each side face
+            // between consecutive strut tops
+            let mut struts = Vec::with_capacity(base.len());
+            for &coedge in &base {
+                let point = self.vertex(self.coedge(coedge)?.vertex)?.point + direction;
+                let (edge, top) = self.mev(coedge, point)?;
+                struts.push((top, self.edge(edge)?.coedges[1]));
+            }
+            for (i, &coedge) in base.iter().enumerate() {
+                let (_, down) = struts[i];
+                let (next_top, _) = struts[(i + 1) % base.len()];
+                let to = self.coedge_from(loop_id, next_top)?;
+                let (edge, side) = self.mef(down, to)?;
+                let curve = self.coedge_curve(coedge)?;
+                let start = self.vertex(self.coedge(coedge)?.vertex)?.point;
+                let end = self.vertex(self.coedge(self.coedge(coedge)?.next)?.vertex)?.point;
+                self.face_mut(side)?.surface = swept_surface(&curve, start, end, &direction);
+                self.edge_mut(edge)?.curve = curve.translated(&direction);
+            }
+        }
+        let face = self.face_mut(face)?;
+        face.surface = face.surface.as_ref().map(|surface| surface.translated(&direction));
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------
+    // Euler operators
+    // ------------------------------------------------------------------
+
+    /// Make a vertex, face and shell: a lone point
+    pub fn mvfs(&mut self, point: Point3) -> (ShellId, FaceId, VertexId) {
+        let shell = ShellId(self.shells.len());
+        self.shells.push(Some(BrepShell::default()));
+        let vertex = self.add_vertex(point);
+        let face = self.add_face(shell, None);
+        let loop_id = LoopId(self.loops.len() - 1);
+        let lone = self.add_coedge(loop_id, None, vertex);
+        if let Some(l) = self.loops[loop_id.0].as_mut() {
+            l.first = lone;
+        }
+        (shell, face, vertex)
+    }
+
+    /// Kill a vertex, face and shell left by [`mvfs`](Self::mvfs)
+    pub fn kvfs(&mut self, shell: ShellId) -> BrepResult<()> {
+        let [face] = self.shell(shell)?.faces[..] else {
+            return Err(BrepError::Precondition("kvfs needs a shell of one face"));
+        };
+        let f = self.face(face)?.clone();
+        let first = self.face_loop(f.outer)?.first;
+        let lone = self.coedge(first)?.clone();
+        if lone.edge.is_some() || !f.rings.is_empty() {
+            return Err(BrepError::Precondition("kvfs needs a face around a lone vertex"));
+        }
+        self.coedges[first.0] = None;
+        self.vertices[lone.vertex.0] = None;
+        self.loops[f.outer.0] = None;
+        self.faces[face.0] = None;
+        self.shells[shell.0] = None;
+        Ok(())
+    }
+
+    /// Make an edge from the start of `at` to a new vertex at `point`
+    ///
+    /// The edge is a strut inserted into the loop just before `at`; its
+    /// first coedge runs out to the new vertex.
+    pub fn mev(&mut self, at: CoedgeId, point: Point3) -> BrepResult<(EdgeId, VertexId)> {
+        let c = self.coedge(at)?.clone();
+        let vertex = self.add_vertex(point);
+        let edge = EdgeId(self.edges.len());
+        let out = self.add_coedge(c.loop_id, Some(edge), c.vertex);
+        let back = self.add_coedge(c.loop_id, Some(edge), vertex);
+        self.edges.push(Some(BrepEdge {
+            curve: EdgeCurve::Line,
+            coedges: [out, back],
+        }));
+
+        let mut cycle = vec![out, back];
+        if c.edge.is_some() {
+            cycle.extend(self.cycle(at)?);
+        } else {
+            self.coedges[at.0] = None;
+        }
+        self.close_loop(c.loop_id, &cycle, c.vertex)?;
+        Ok((edge, vertex))
+    }
+
+    /// Kill a strut edge and the vertex at its free end
+    pub fn kev(&mut self, edge: EdgeId) -> BrepResult<VertexId> {
+        let [a, b] = self.edge(edge)?.coedges;
+        let (first, second) = if self.coedge(a)?.next == b {
+            (a, b)
+        } else if self.coedge(b)?.next == a {
+            (b, a)
+        } else {
+            return Err(BrepError::Precondition("kev needs an edge to a free vertex"));
+        };
+        let (base, tip) = (self.coedge(first)?.vertex, self.coedge(second)?.vertex);
+        let loop_id = self.coedge(first)?.loop_id;
+        let rest = self.walk(self.coedge(second)?.next, first)?;
+        self.coedges[first.0] = None;
+        self.coedges[second.0] = None;
+        self.edges[edge.0] = None;
+        self.vertices[tip.0] = None;
+        self.close_loop(loop_id, &rest, base)?;
+        Ok(tip)
+    }
+
+    /// Make an edge from the start of `from` to the start of `to`, splitting
+    /// their loop into two faces
+    ///
+    /// The new face is bounded by `from` up to `to` and the edge's second
+    /// coedge; the old face keeps the rest and the first coedge, which runs
+    /// from `from` to `to`.
+    pub fn mef(&mut self, from: CoedgeId, to: CoedgeId) -> BrepResult<(EdgeId, FaceId)> {
+        let (cf, ct) = (self.coedge(from)?.clone(), self.coedge(to)?.clone());
+        if from == to || cf.loop_id != ct.loop_id || cf.edge.is_none() {
+            return Err(BrepError::Precondition("mef needs two coedges of one loop"));
+        }
+        let old_face = self.face_loop(cf.loop_id)?.face;
+        let shell = self.face(old_face)?.shell;
+        let face = self.add_face(shell, None);
+        let new_loop = self.face(face)?.outer;
+
+        let edge = EdgeId(self.edges.len());
+        let forward = self.add_coedge(cf.loop_id, Some(edge), cf.vertex);
+        let backward = self.add_coedge(new_loop, Some(edge), ct.vertex);
+        self.edges.push(Some(BrepEdge {
+            curve: EdgeCurve::Line,
+            coedges: [forward, backward],
+        }));
+
+        let mut split = self.walk(from, to)?;
+        split.push(backward);
+        let mut kept = vec![forward];
+        kept.extend(self.walk(to, from)?);
+        self.close_loop(new_loop, &split, ct.vertex)?;
+        self.close_loop(cf.loop_id, &kept, cf.vertex)?;
+        Ok((edge, face))
+    }
+
+    /// Kill an edge between two faces, merging the face on its second
+    /// coedge's side into the face on its first's
+    pub fn kef(&mut self, edge: EdgeId) -> BrepResult<FaceId> {
+        let [a, b] = self.edge(edge)?.coedges;
+        let (ca, cb) = (self.coedge(a)?.clone(), self.coedge(b)?.clone());
+        let (keep, kill) = (self.face_loop(ca.loop_id)?.face, self.face_loop(cb.loop_id)?.face);
+        if keep == kill {
+            return Err(BrepError::Precondition("kef needs different faces on either side"));
+        }
+        let killed = self.face(kill)?.clone();
+        if killed.outer != cb.loop_id {
+            return Err(BrepError::Precondition("kef needs the edge on the killed face's outer loop"));
+        }
+
+        let mut merged = self.walk(ca.next, a)?;
+        merged.extend(self.walk(cb.next, b)?);
+        self.coedges[a.0] = None;
+        self.coedges[b.0] = None;
+        self.edges[edge.0] = None;
+        self.loops[cb.loop_id.0] = None;
+        self.close_loop(ca.loop_id, &merged, ca.vertex)?;
+
+        for &ring in &killed.rings {
+            self.loop_mut(ring)?.face = keep;
+        }
+        self.face_mut(keep)?.rings.extend(killed.rings);
+        self.shell_mut(killed.shell)?.faces.retain(|&f| f != kill);
+        self.faces[kill.0] = None;
+        Ok(kill)
+    }
+
+    /// Kill the edge under `at`, which must have its loop on both sides,
+    /// making the part of the loop after `at` a new ring
+    pub fn kemr(&mut self, at: CoedgeId) -> BrepResult<LoopId> {
+        let c = self.coedge(at)?.clone();
+        let edge = c.edge.ok_or(BrepError::Precondition("kemr needs an edge"))?;
+        let [a, b] = self.edge(edge)?.coedges;
+        let mate = if a == at { b } else { a };
+        let cm = self.coedge(mate)?.clone();
+        if cm.loop_id != c.loop_id {
+            return Err(BrepError::Precondition("kemr needs both sides of the edge in one loop"));
+        }
+        let face = self.face_loop(c.loop_id)?.face;
+
+        let inner = self.walk(c.next, mate)?;
+        let rest = self.walk(cm.next, at)?;
+        self.coedges[at.0] = None;
+        self.coedges[mate.0] = None;
+        self.edges[edge.0] = None;
+        let ring = self.add_loop(face);
+        self.face_mut(face)?.rings.push(ring);
+        self.close_loop(ring, &inner, cm.vertex)?;
+        self.close_loop(c.loop_id, &rest, c.vertex)?;
+        Ok(ring)
+    }
+
+    /// Make an edge from the start of `from` to the start of `to`, joining
+    /// their two loops of one face and killing the ring among them
+    pub fn mekr(&mut self, from: CoedgeId, to: CoedgeId) -> BrepResult<EdgeId> {
+        let (cf, ct) = (self.coedge(from)?.clone(), self.coedge(to)?.clone());
+        let face = self.face_loop(cf.loop_id)?.face;
+        if cf.loop_id == ct.loop_id || self.face_loop(ct.loop_id)?.face != face {
+            return Err(BrepError::Precondition("mekr needs two loops of one face"));
+        }
+        let (keep, kill) = if self.face(face)?.outer == ct.loop_id {
+            (ct.loop_id, cf.loop_id)
+        } else {
+            (cf.loop_id, ct.loop_id)
+        };
+
+        let edge = EdgeId(self.edges.len());
+        let forward = self.add_coedge(keep, Some(edge), cf.vertex);
+        let backward = self.add_coedge(keep, Some(edge), ct.vertex);
+        self.edges.push(Some(BrepEdge {
+            curve: EdgeCurve::Line,
+            coedges: [forward, backward],
+        }));
+
+        let mut joined = vec![forward];
+        joined.extend(self.cycle(to)?);
+        joined.push(backward);
+        joined.extend(self.cycle(from)?);
+        for lone in [from, to] {
+            if self.coedge(lone)?.edge.is_none() {
+                self.coedges[lone.0] = None;
+            }
+        }
+        self.loops[kill.0] = None;
+        self.face_mut(face)?.rings.retain(|&ring| ring != kill);
+        self.close_loop(keep, &joined, cf.vertex)?;
+        Ok(edge)
+    }
+
+    /// Kill a face without rings, making its loop a ring of `into` and
+    /// a hole through the solid
+    pub fn kfmrh(&mut self, face: FaceId, into: FaceId) -> BrepResult<()> {
+        let killed = self.face(face)?.clone();
+        if face == into || !killed.rings.is_empty() || self.face(into)?.shell != killed.shell {
+            return Err(BrepError::Precondition("kfmrh needs a face without rings and another of its shell"));
+        }
+        self.loop_mut(killed.outer)?.face = into;
+        self.face_mut(into)?.rings.push(killed.outer);
+        self.shell_mut(killed.shell)?.faces.retain(|&f| f != face);
+        self.faces[face.0] = None;
+        Ok(())
+    }
+
+    /// Make a face bounded by a ring, killing the ring and a hole
+    pub fn mfkrh(&mut self, ring: LoopId) -> BrepResult<FaceId> {
+        let owner = self.face_loop(ring)?.face;
+        let shell = self.face(owner)?.shell;
+        if !self.face(owner)?.rings.contains(&ring) {
+            return Err(BrepError::Precondition("mfkrh needs a ring"));
+        }
+        self.face_mut(owner)?.rings.retain(|&r| r != ring);
+        let face = FaceId(self.faces.len());
+        self.faces.push(Some(BrepFace {
+            shell,
+            outer: ring,
+            rings: Vec::new(),
+            surface: None,
+        }));
+        self.shell_mut(shell)?.faces.push(face);
+        self.loop_mut(ring)?.face = face;
+        Ok(face)
+    }
+
+    // ------------------------------------------------------------------
+    // Queries
+    // ------------------------------------------------------------------
+
+    /// Vertex by handle
+    pub fn vertex(&self, id: VertexId) -> BrepResult<&BrepVertex> {
+        slot(&self.vertices, id.0, "vertex")
+    }
+
+    /// Edge by handle
+    pub fn edge(&self, id: EdgeId) -> BrepResult<&BrepEdge> {
+        slot(&self.edges, id.0, "edge")
+    }
+
+    /// Coedge by handle
+    pub fn coedge(&self, id: CoedgeId) -> BrepResult<&Coedge> {
+        slot(&self.coedges, id.0, "coedge")
+    }
+
+    /// Loop by handle
+    pub fn face_loop(&self, id: LoopId) -> BrepResult<&BrepLoop> {
+        slot(&self.loops, id.0, "loop")
+    }
+
+    /// Face by handle
+    pub fn face(&self, id: FaceId) -> BrepResult<&BrepFace> {
+        slot(&self.faces, id.0, "face")
+    }
+
+    /// Shell by handle
+    pub fn shell(&self, id: ShellId) -> BrepResult<&BrepShell> {
+        slot(&self.shells, id.0, "shell")
+    }
+
+    /// Live vertices
+    pub fn vertex_ids(&self) -> Vec<VertexId> {
+        live(&self.vertices).map(VertexId).collect()
+    }
+
+    /// Live edges
+    pub fn edge_ids(&self) -> Vec<EdgeId> {
+        live(&self.edges).map(EdgeId).collect()
+    }
+
+    /// Live faces
+    pub fn face_ids(&self) -> Vec<FaceId> {
+        live(&self.faces).map(FaceId).collect()
+    }
+
+    /// Live shells
+    pub fn shell_ids(&self) -> Vec<ShellId> {
+        live(&self.shells).map(ShellId).collect()
+    }
+
+    /// Number of inner rings over all faces
+    pub fn ring_count(&self) -> usize {
+        self.faces.iter().flatten().map(|face| face.rings.len()).sum()
+    }
+
+    /// Coedges of a loop in order, empty around a lone vertex
+    pub fn loop_coedges(&self, id: LoopId) -> BrepResult<Vec<CoedgeId>> {
+        self.cycle(self.face_loop(id)?.first)
+    }
+
+    /// Start and end vertices of an edge
+    pub fn edge_vertices(&self, id: EdgeId) -> BrepResult<(VertexId, VertexId)> {
+        let [a, b] = self.edge(id)?.coedges;
+        Ok((self.coedge(a)?.vertex, self.coedge(b)?.vertex))
+    }
+
+    /// Curve of the edge under a coedge, running the way the coedge does
+    pub fn coedge_curve(&self, id: CoedgeId) -> BrepResult<EdgeCurve> {
+        let edge_id = self.coedge(id)?.edge.ok_or(BrepError::Precondition("coedge has no edge"))?;
+        let edge = self.edge(edge_id)?;
+        Ok(if edge.coedges[0] == id { edge.curve.clone() } else { edge.curve.reversed() })
+    }
+
+    /// Move a vertex, leaving the curves and surfaces around it as they are
+    pub fn set_vertex_point(&mut self, id: VertexId, point: Point3) -> BrepResult<()> {
+        slot_mut(&mut self.vertices, id.0, "vertex")?.point = point;
+        Ok(())
+    }
+
+    /// Set an edge's curve
+    pub fn set_edge_curve(&mut self, id: EdgeId, curve: EdgeCurve) -> BrepResult<()> {
+        self.edge_mut(id)?.curve = curve;
+        Ok(())
+    }
+
+    /// Set a face's surface
+    pub fn set_face_surface(&mut self, id: FaceId, surface: Option<FaceSurface>) -> BrepResult<()> {
+        self.face_mut(id)?.surface = surface;
+        Ok(())
+    }
+
+    /// Number of through holes, from the Euler–Poincaré formula
+    pub fn genus(&self) -> BrepResult<usize> {
+        let [v, e, f, r, s] = [
+            self.vertex_ids().len(),
+            self.edge_ids().len(),
+            self.face_ids().len(),
+            self.ring_count(),
+            self.shell_ids().len(),
+        ]
+        .map(|n| n as i64);
+        let euler = v - e + f - r;
+        let twice_genus = 2 * s - euler;
+        if twice_genus < 0 || twice_genus % 2 != 0 {
+            return Err(BrepError::Inconsistent(format!("Euler characteristic {euler} is impossible")));
+        }
+        Ok((twice_genus / 2) as usize)
+    }
+
+    /// Check that loops close, edges pair opposite coedges, every element
+    /// is referenced by its owner and the Euler–Poincaré formula holds
+    pub fn validate(&self) -> BrepResult<()> {
+        let inconsistent = |what: String| Err(BrepError::Inconsistent(what));
+        let mut reached = 0;
+        for (index, l) in self.loops.iter().enumerate() {
+            let Some(l) = l else { continue };
+            let loop_id = LoopId(index);
+            let face = self.face(l.face)?;
+            if face.outer != loop_id && !face.rings.contains(&loop_id) {
+                return inconsistent(format!("loop {index} is not on its face"));
+            }
+            let cycle = self.cycle(l.first)?;
+            reached += cycle.len().max(1);
+            for &c in &cycle {
+                let coedge = self.coedge(c)?;
+                if coedge.loop_id != loop_id || self.coedge(coedge.next)?.prev != c {
+                    return inconsistent(format!("coedge {} is misplaced in loop {index}", c.0));
+                }
+            }
+        }
+        if reached != live(&self.coedges).count() {
+            return inconsistent("coedges outside any loop".to_string());
+        }
+
+        for id in self.edge_ids() {
+            let [a, b] = self.edge(id)?.coedges;
+            let (ca, cb) = (self.coedge(a)?, self.coedge(b)?);
+            let ends = (self.coedge(ca.next)?.vertex, self.coedge(cb.next)?.vertex);
+            if ca.edge != Some(id) || cb.edge != Some(id) || ends != (cb.vertex, ca.vertex) {
+                return inconsistent(format!("edge {} is not used both ways", id.0));
+            }
+        }
+
+        for (index, shell) in self.shells.iter().enumerate() {
+            let Some(shell) = shell else { continue };
+            for &face in &shell.faces {
+                if self.face(face)?.shell != ShellId(index) {
+                    return inconsistent(format!("face {} is in the wrong shell", face.0));
+                }
+            }
+        }
+        for id in self.face_ids() {
+            let face = self.face(id)?;
+            if !self.shell(face.shell)?.faces.contains(&id) {
+                return inconsistent(format!("face {} is missing from its shell", id.0));
+            }
+        }
+
+        self.genus().map(|_| ())
+    }
+
+    // ------------------------------------------------------------------
+    // Tessellation
+    // ------------------------------------------------------------------
+
+    /// Triangle mesh of the solid within `settings`
+    ///
+    /// Edges are discretized once and shared by the faces on both sides,
+    /// so the mesh has no cracks. Faces are triangulated from their
+    /// boundaries in a chart of their surface: planes exactly, analytic
+    /// surfaces unrolled around their axis, and NURBS faces projected onto
+    /// their best-fit plane. Curved faces gain no interior points, so
+    /// their facets follow the surface only along the boundary.
+    pub fn to_mesh(&self, settings: &TessellationSettings) -> BrepResult<HalfEdgeMesh> {
+        let tessellator = AdaptiveTessellator::new(*settings);
+        let mut mesh = HalfEdgeMesh::new();
+        let mut corners = HashMap::new();
+        for id in self.vertex_ids() {
+            corners.insert(id, mesh.add_vertex(self.vertex(id)?.point));
+        }
+        let mut interiors = HashMap::new();
+        for id in self.edge_ids() {
+            let points = self.edge_points(id, &tessellator, settings)?;
+            let handles: Vec<_> = points[1..points.len() - 1]
+                .iter()
+                .map(|p| (mesh.add_vertex(*p), *p))
+                .collect();
+            interiors.insert(id, handles);
+        }
+
+        for id in self.face_ids() {
+            let face = self.face(id)?;
+            let mut rings: Vec<Vec<(VertexHandle, Point3)>> = Vec::new();
+            for loop_id in std::iter::once(face.outer).chain(face.rings.iter().copied()) {
+                let mut ring = Vec::new();
+                for c in self.loop_coedges(loop_id)? {
+                    let coedge = self.coedge(c)?;
+                    let vertex = coedge.vertex;
+                    ring.push((corners[&vertex], self.vertex(vertex)?.point));
+                    let Some(edge) = coedge.edge else { continue };
+                    let along = &interiors[&edge];
+                    if self.edge(edge)?.coedges[0] == c {
+                        ring.extend(along.iter().copied());
+                    } else {
+                        ring.extend(along.iter().rev().copied());
+                    }
+                }
+                rings.push(ring);
+            }
+            if rings[0].len() < 3 {
+                continue;
+            }
+
+            let outer_points: Vec<_> = rings[0].iter().map(|(_, p)| *p).collect();
+            let chart = Chart::new(face.surface.as_ref(), &outer_points)?;
+            let mut reference = None;
+            let flat: Vec<Vec<Point2D>> = rings
+                .iter()
+                .map(|ring| chart.flatten(ring.iter().map(|(_, p)| p), &mut reference))
+                .collect();
+            let handles: Vec<_> = rings.iter().flatten().map(|(h, _)| *h).collect();
+            // Loops run counter-clockwise about the outward normal, so a
+            // clockwise outer loop means the chart is mirrored
+            let mirrored = signed_area(&flat[0]) < 0.0;
+            let fill = tessellate(&flat[0], &flat[1..]);
+            let mut triangles: Vec<[usize; 3]> = fill
+                .indices
+                .chunks_exact(3)
+                .map(|t| {
+                    let [a, b, c] = [0, 1, 2].map(|k| t[k] as usize);
+                    if orient(fill.vertices[a], fill.vertices[b], fill.vertices[c]) < 0.0 {
+                        [a, c, b]
+                    } else {
+                        [a, b, c]
+                    }
+                })
+                .collect();
+            make_delaunay(&fill.vertices, &mut triangles);
+            for triangle in triangles {
+                let [a, b, c] = triangle.map(|k| handles[k]);
+                if a == b || b == c || c == a {
+                    continue;
+                }
+                if mirrored {
+                    mesh.add_face(&[a, c, b])?;
+                } else {
+                    mesh.add_face(&[a, b, c])?;
+                }
+            }
+        }
+        mesh.update_vertex_normals();
+        Ok(mesh)
+    }
+
+    /// Points along an edge from its start vertex to its end vertex
+    fn edge_points(
+        &self,
+        id: EdgeId,
+        tessellator: &AdaptiveTessellator,
+        settings: &TessellationSettings,
+    ) -> BrepResult<Vec<Point3>> {
+        let (start, end) = self.edge_vertices(id)?;
+        let (a, b) = (self.vertex(start)?.point, self.vertex(end)?.point);
+        let mut points = match &self.edge(id)?.curve {
+            EdgeCurve::Line => vec![a, b],
+            EdgeCurve::Arc { center, axis } => {
+                let frame = Frame::new(*center, *axis);
+                let local = |p: Point3| {
+                    let d = p - center;
+                    (d.dot(&frame.x_axis), d.dot(&frame.y_axis), d.dot(&frame.z_axis))
+                };
+                let (x, y, height) = local(a);
+                let radius = x.hypot(y);
+                let start_angle = y.atan2(x);
+                let (xb, yb, _) = local(b);
+                let mut sweep = (yb.atan2(xb) - start_angle).rem_euclid(TAU);
+                if sweep < EPSILON || start == end {
+                    sweep = TAU;
+                }
+                let n = settings.arc_segments(radius, sweep);
+                (0..=n)
+                    .map(|k| {
+                        let angle = start_angle + sweep * k as f64 / n as f64;
+                        center
+                            + frame.z_axis * height
+                            + (frame.x_axis * angle.cos() + frame.y_axis * angle.sin()) * radius
+                    })
+                    .collect()
+            }
+            EdgeCurve::Nurbs(curve) => tessellator.tessellate_curve(curve)?,
+        };
+        // Ends sit exactly on the vertices
+        let last = points.len() - 1;
+        points[0] = a;
+        points[last] = b;
+        Ok(points)
+    }
+
+    // ------------------------------------------------------------------
+    // Internals
+    // ------------------------------------------------------------------
+
+    fn edge_mut(&mut self, id: EdgeId) -> BrepResult<&mut BrepEdge> {
+        slot_mut(&mut self.edges, id.0, "edge")
+    }
+
+    fn coedge_mut(&mut self, id: CoedgeId) -> BrepResult<&mut Coedge> {
+        slot_mut(&mut self.coedges, id.0, "coedge")
+    }
+
+    fn loop_mut(&mut self, id: LoopId) -> BrepResult<&mut BrepLoop> {
+        slot_mut(&mut self.loops, id.0, "loop")
+    }
+
+    fn face_mut(&mut self, id: FaceId) -> BrepResult<&mut BrepFace> {
+        slot_mut(&mut self.faces, id.0, "face")
+    }
+
+    fn shell_mut(&mut self, id: ShellId) -> BrepResult<&mut BrepShell> {
+        slot_mut(&mut self.shells, id.0, "shell")
+    }
+
+    fn add_vertex(&mut self, point: Point3) -> VertexId {
+        self.vertices.push(Some(BrepVertex { point }));
+        VertexId(self.vertices.len() - 1)
+    }
+
+    /// Coedge linked to itself; [`close_loop`](Self::close_loop) links it in
+    fn add_coedge(&mut self, loop_id: LoopId, edge: Option<EdgeId>, vertex: VertexId) -> CoedgeId {
+        let id = CoedgeId(self.coedges.len());
+        self.coedges.push(Some(Coedge {
+            loop_id,
+            edge,
+            vertex,
+            next: id,
+            prev: id,
+        }));
+        id
+    }
+
+    /// Loop with no coedges yet
+    fn add_loop(&mut self, face: FaceId) -> LoopId {
+        self.loops.push(Some(BrepLoop {
+            face,
+            first: CoedgeId(usize::MAX),
+        }));
+        LoopId(self.loops.len() - 1)
+    }
+
+    /// Face in `shell` with an empty outer loop
+    fn add_face(&mut self, shell: ShellId, surface: Option<FaceSurface>) -> FaceId {
+        let face = FaceId(self.faces.len());
+        let outer = self.add_loop(face);
+        self.faces.push(Some(BrepFace {
+            shell,
+            outer,
+            rings: Vec::new(),
+            surface,
+        }));
+        if let Some(shell) = self.shells[shell.0].as_mut() {
+            shell.faces.push(face);
+        }
+        face
+    }
+
+    /// Link `cycle` into loop `loop_id`, or leave a lone coedge at `vertex`
+    /// when it is empty
+    fn close_loop(&mut self, loop_id: LoopId, cycle: &[CoedgeId], vertex: VertexId) -> BrepResult<()> {
+        if cycle.is_empty() {
+            let lone = self.add_coedge(loop_id, None, vertex);
+            self.loop_mut(loop_id)?.first = lone;
+            return Ok(());
+        }
+        for (k, &id) in cycle.iter().enumerate() {
+            let coedge = self.coedge_mut(id)?;
+            coedge.loop_id = loop_id;
+            coedge.next = cycle[(k + 1) % cycle.len()];
+            coedge.prev = cycle[(k + cycle.len() - 1) % cycle.len()];
+        }
+        self.loop_mut(loop_id)?.first = cycle[0];
+        Ok(())
+    }
+
+    /// Coedges from `start` up to but excluding `stop`
+    fn walk(&self, start: CoedgeId, stop: CoedgeId) -> BrepResult<Vec<CoedgeId>> {
+        let mut coedges = Vec::new();
+        let mut current = start;
+        while current != stop {
+            if coedges.len() > self.coedges.len() {
+                return Err(BrepError::Inconsistent(format!("loop through coedge {} never closes", start.0)));
+            }
+            coedges.push(current);
+            current = self.coedge(current)?.next;
+        }
+        Ok(coedges)
+    }
+
+    /// Whole loop starting at `start`, empty for a lone vertex
+    fn cycle(&self, start: CoedgeId) -> BrepResult<Vec<CoedgeId>> {
+        let coedge = self.coedge(start)?;
+        if coedge.edge.is_none() {
+            return Ok(Vec::new());
+        }
+        let mut coedges = vec![start];
+        coedges.extend(self.walk(coedge.next, start)?);
+        Ok(coedges)
+    }
+
+    /// The coedge of a loop leaving `vertex`
+    fn coedge_from(&self, loop_id: LoopId, vertex: VertexId) -> BrepResult<CoedgeId> {
+        let first = self.face_loop(loop_id)?.first;
+        let cycle = self.cycle(first)?;
+        cycle
+            .into_iter()
+            .find(|&c| self.coedge(c).is_ok_and(|c| c.vertex == vertex))
+            .ok_or(BrepError::Precondition("vertex is not on the loop"))
+    }
+}
+
+/// Surface swept by `curve` from `start` to `end` moving along `direction`
+fn swept_surface(curve: &EdgeCurve, start: Point3, end: Point3, direction: &Vector3) -> Option<FaceSurface> {
+    match curve {
+        EdgeCurve::Line => (end - start)
+            .cross(direction)
+            .try_normalize(EPSILON)
+            .map(|normal| FaceSurface::Plane { origin: start, normal }),
+        EdgeCurve::Arc { center, axis } => {
+            let axis = axis.try_normalize(EPSILON)?;
+            let height = direction.norm();
+            if axis.cross(direction).norm() > EPSILON * height.max(1.0) {
+                return None;
+            }
+            let offset = start - center;
+            let radius = (offset - axis * offset.dot(&axis)).norm();
+            Some(FaceSurface::Analytic(AnalyticSurface::cylinder(
+                *center,
+                direction / height,
+                radius,
+                height,
+            )))
+        }
+        EdgeCurve::Nurbs(curve) => Some(FaceSurface::Nurbs(NurbsSurface {
+            degree_u: curve.degree,
+            degree_v: 1,
+            control_points: curve
+                .control_points
+                .iter()
+                .map(|p| vec![*p, translate_homogeneous(p, direction)])
+                .collect(),
+            knots_u: curve.knots.clone(),
+            knots_v: vec![0.0, 0.0, 1.0, 1.0],
+        })),
+    }
+}
+
+/// Homogeneous control point moved by `offset`
+fn translate_homogeneous(p: &[f64; 4], offset: &Vector3) -> [f64; 4] {
+    [p[0] + offset.x * p[3], p[1] + offset.y * p[3], p[2] + offset.z * p[3], p[3]]
+}
+
+/// Flat coordinates over a face's surface for triangulating its boundary
+struct Chart {
+    frame: Frame,
+    kind: ChartKind,
+}
+
+enum ChartKind {
+    Planar,
+    /// Angle about the axis scaled by a typical radius, and height
+    Cylindrical(f64),
+    /// Longitude and latitude scaled by the radius
+    Spherical(f64),
+    /// Angles around the axis and the tube, scaled by the radii
+    Toroidal(f64, f64),
+}
+
+impl Chart {
+    fn new(surface: Option<&FaceSurface>, outer: &[Point3]) -> BrepResult<Self> {
+        let chart = |frame: Frame, kind| Ok(Self { frame, kind });
+        match surface {
+            Some(FaceSurface::Plane { origin, normal }) => {
+                chart(Frame::new(*origin, *normal), ChartKind::Planar)
+            }
+            Some(FaceSurface::Analytic(surface)) => match *surface {
+                AnalyticSurface::Plane { frame, .. } => chart(frame, ChartKind::Planar),
+                AnalyticSurface::Cylinder { frame, radius, .. } => {
+                    chart(frame, ChartKind::Cylindrical(radius))
+                }
+                AnalyticSurface::Cone {
+                    frame,
+                    half_angle,
+                    height,
+                } => chart(frame, ChartKind::Cylindrical(height * half_angle.tan())),
+                AnalyticSurface::Sphere { frame, radius } => chart(frame, ChartKind::Spherical(radius)),
+                AnalyticSurface::Torus {
+                    frame,
+                    major_radius,
+                    minor_radius,
+                } => chart(frame, ChartKind::Toroidal(major_radius, minor_radius)),
+            },
+            Some(FaceSurface::Nurbs(_)) | None => {
+                let normal = newell_normal(outer);
+                if normal.norm() < EPSILON {
+                    return Err(BrepError::Inconsistent("face boundary encloses no area".to_string()));
+                }
+                chart(Frame::new(outer[0], normal), ChartKind::Planar)
+            }
+        }
+    }
+
+    /// Chart coordinates of a loop, unwrapping angles so that each point
+    /// stays within half a turn of the one before; `reference` carries the
+    /// last angles over from one loop of a face to the next
+    fn flatten<'a>(
+        &self,
+        points: impl Iterator<Item = &'a Point3>,
+        reference: &mut Option<(f64, f64)>,
+    ) -> Vec<Point2D> {
+        points
+            .map(|p| {
+                let d = p - self.frame.origin;
+                let Frame {
+                    x_axis, y_axis, z_axis, ..
+                } = &self.frame;
+                let (x, y, z) = (d.dot(x_axis), d.dot(y_axis), d.dot(z_axis));
+                if let ChartKind::Planar = self.kind {
+                    return Point2D::new(x, y);
+                }
+                let around = y.atan2(x);
+                let across = match self.kind {
+                    ChartKind::Spherical(_) => z.atan2(x.hypot(y)),
+                    ChartKind::Toroidal(major, _) => z.atan2(x.hypot(y) - major),
+                    _ => z,
+                };
+                let (around, across) = match *reference {
+                    Some((u, v)) => (
+                        unwrap(around, u),
+                        if let ChartKind::Toroidal(..) = self.kind { unwrap(across, v) } else { across },
+                    ),
+                    None => (around, across),
+                };
+                *reference = Some((around, across));
+                match self.kind {
+                    ChartKind::Cylindrical(scale) => Point2D::new(around * scale, across),
+                    ChartKind::Spherical(radius) => Point2D::new(around * radius, across * radius),
+                    ChartKind::Toroidal(major, minor) => Point2D::new(around * major, across * minor),
+                    ChartKind::Planar => unreachable!(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Flip interior edges of a counter-clockwise triangulation until each is
+/// locally Delaunay, so that faces on curved surfaces are not spanned by
+/// long slivers cutting through the solid
+fn make_delaunay(points: &[Point2D], triangles: &mut [[usize; 3]]) {
+    for _ in 0..=triangles.len() {
+        let mut opposite = HashMap::new();
+        for (t, tri) in triangles.iter().enumerate() {
+            for k in 0..3 {
+                opposite.insert((tri[k], tri[(k + 1) % 3]), (t, tri[(k + 2) % 3]));
+            }
+        }
+        let mut touched = vec![false; triangles.len()];
+        for t in 0..triangles.len() {
+            for k in 0..3 {
+                let [a, b, c] = [0, 1, 2].map(|i| triangles[t][(k + i) % 3]);
+                let Some(&(u, d)) = opposite.get(&(b, a)) else { continue };
+                if touched[t] || touched[u] {
+                    continue;
+                }
+                let [pa, pb, pc, pd] = [a, b, c, d].map(|i| points[i]);
+                let convex = orient(pa, pd, pc) > 0.0 && orient(pd, pb, pc) > 0.0;
+                if convex && in_circle(pa, pb, pc, pd) > EPSILON {
+                    triangles[t] = [a, d, c];
+                    triangles[u] = [d, b, c];
+                    touched[t] = true;
+                    touched[u] = true;
+                }
+            }
+        }
+        if !touched.contains(&true) {
+            return;
+        }
+    }
+}
+
+/// Twice the signed area of triangle `abc`
+fn orient(a: Point2D, b: Point2D, c: Point2D) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// Positive when `d` lies inside the circumcircle of counter-clockwise `abc`
+fn in_circle(a: Point2D, b: Point2D, c: Point2D, d: Point2D) -> f64 {
+    let rows = [a, b, c].map(|p| {
+        let (x, y) = (p.x - d.x, p.y - d.y);
+        [x, y, x * x + y * y]
+    });
+    let [r0, r1, r2] = rows;
+    r0[0] * (r1[1] * r2[2] - r2[1] * r1[2]) - r0[1] * (r1[0] * r2[2] - r2[0] * r1[2])
+        + r0[2] * (r1[0] * r2[1] - r2[0] * r1[1])
+}
+
+/// `angle` shifted by whole turns to within half a turn of `near`
+fn unwrap(angle: f64, near: f64) -> f64 {
+    angle + TAU * ((near - angle + PI) / TAU).floor()
+}
+
+/// Twice the signed area of a polygon, positive when counter-clockwise
+fn signed_area(points: &[Point2D]) -> f64 {
+    (0..points.len())
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum()
+}
+
+fn slot<'a, T>(items: &'a [Option<T>], index: usize, kind: &'static str) -> BrepResult<&'a T> {
+    items.get(index).and_then(Option::as_ref).ok_or(BrepError::InvalidHandle(kind))
+}
+
+fn slot_mut<'a, T>(items: &'a mut [Option<T>], index: usize, kind: &'static str) -> BrepResult<&'a mut T> {
+    items.get_mut(index).and_then(Option::as_mut).ok_or(BrepError::InvalidHandle(kind))
+}
+
+fn live<T>(items: &[Option<T>]) -> impl Iterator<Item = usize> + '_ {
+    items.iter().enumerate().filter(|(_, item)| item.is_some()).map(|(index, _)| index)
+}
+
+/// B-rep errors
+#[derive(Debug, thiserror::Error)]
+pub enum BrepError {
+    #[error("Invalid {0} handle")]
+    InvalidHandle(&'static str),
+
+    #[error("Euler operator precondition failed: {0}")]
+    Precondition(&'static str),
+
+    #[error("Inconsistent topology: {0}")]
+    Inconsistent(String),
+
+    #[error("Profile is degenerate or parallel to the direction")]
+    DegenerateProfile,
+
+    #[error(transparent)]
+    Mesh(#[from] MeshError),
+
+    #[error(transparent)]
+    Tessellation(#[from] TessellationError),
+}
+
+/// Result type for B-rep operations
+pub type BrepResult<T> = Result<T, BrepError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Enclosed volume of a closed triangle mesh
+    fn volume(mesh: &HalfEdgeMesh) -> f64 {
+        mesh.face_handles()
+            .iter()
+            .map(|&fh| {
+                let p: Vec<_> = mesh
+                    .face_vertices(fh)
+                    .unwrap()
+                    .iter()
+                    .map(|&vh| mesh.get_vertex(vh).unwrap().position.coords)
+                    .collect();
+                p[0].dot(&p[1].cross(&p[2])) / 6.0
+            })
+            .sum()
+    }
+
+    fn closed(mesh: &HalfEdgeMesh) -> bool {
+        mesh.halfedges.iter().flatten().all(|he| {
+            let twin = he.twin.and_then(|t| mesh.halfedges[t.0].as_ref());
+            he.face.is_some() && twin.is_some_and(|t| t.face.is_some())
+        })
+    }
+
+    #[test]
+    fn test_euler_operators() {
+        // Triangle lamina, then taken apart again by the inverses
+        let mut brep = Brep::new();
+        let (shell, face, _) = brep.mvfs(Point3::new(0.0, 0.0, 0.0));
+        let lone = brep.face_loop(brep.face(face).unwrap().outer).unwrap().first;
+        let (e1, _) = brep.mev(lone, Point3::new(1.0, 0.0, 0.0)).unwrap();
+        let back = brep.edge(e1).unwrap().coedges[1];
+        let (e2, _) = brep.mev(back, Point3::new(0.0, 1.0, 0.0)).unwrap();
+        let tip = brep.edge(e2).unwrap().coedges[1];
+        let first = brep.edge(e1).unwrap().coedges[0];
+        let (e3, _) = brep.mef(tip, first).unwrap();
+        assert_eq!((brep.vertex_ids().len(), brep.edge_ids().len(), brep.face_ids().len()), (3, 3, 2));
+        brep.validate().unwrap();
+
+        brep.kef(e3).unwrap();
+        brep.kev(e2).unwrap();
+        brep.kev(e1).unwrap();
+        brep.validate().unwrap();
+        brep.kvfs(shell).unwrap();
+        assert!(brep.vertex_ids().is_empty() && brep.face_ids().is_empty());
+
+        // Block with a square hole through it
+        let square = |half: f64| {
+            vec![
+                Point3::new(-half, -half, 0.0),
+                Point3::new(half, -half, 0.0),
+                Point3::new(half, half, 0.0),
+                Point3::new(-half, half, 0.0),
+            ]
+        };
+        let region = ProfileRegion::new(square(2.0)).with_hole(square(1.0));
+        let block = Brep::extrude(&region, Vector3::new(0.0, 0.0, 1.0)).unwrap();
+        block.validate().unwrap();
+        assert_eq!(block.vertex_ids().len(), 16);
+        assert_eq!(block.edge_ids().len(), 24);
+        assert_eq!(block.face_ids().len(), 10);
+        assert_eq!(block.ring_count(), 2);
+        assert_eq!(block.genus().unwrap(), 1);
+
+        let mesh = block.to_mesh(&TessellationSettings::default()).unwrap();
+        assert!(closed(&mesh));
+        assert!((volume(&mesh) - 12.0).abs() < 1e-9);
+
+        // Cutting the ring back off the top face restores a simple loop
+        let top = block
+            .face_ids()
+            .into_iter()
+            .find(|&f| block.face(f).unwrap().rings.len() == 1)
+            .unwrap();
+        let mut block = block;
+        let ring = block.face(top).unwrap().rings[0];
+        let outer = block.face(top).unwrap().outer;
+        let from = block.loop_coedges(outer).unwrap()[0];
+        let to = block.loop_coedges(ring).unwrap()[0];
+        block.mekr(from, to).unwrap();
+        block.validate().unwrap();
+        assert_eq!(block.ring_count(), 1);
+    }
+
+    #[test]
+    fn test_cylinder_mesh() {
+        let brep = Brep::cylinder(Point3::new(1.0, 2.0, 0.0), Vector3::new(0.0, 0.0, 1.0), 1.0, 2.0).unwrap();
+        brep.validate().unwrap();
+        assert_eq!((brep.vertex_ids().len(), brep.edge_ids().len(), brep.face_ids().len()), (4, 6, 4));
+        let curved = brep
+            .face_ids()
+            .into_iter()
+            .filter(|&f| matches!(brep.face(f).unwrap().surface, Some(FaceSurface::Analytic(_))))
+            .count();
+        assert_eq!(curved, 2);
+
+        let settings = TessellationSettings::default();
+        let mesh = brep.to_mesh(&settings).unwrap();
+        assert!(closed(&mesh));
+        for v in mesh.vertices.iter().flatten() {
+            let radial = (v.position.x - 1.0).hypot(v.position.y - 2.0);
+            assert!((radial - 1.0).abs() < 1e-9);
+        }
+        // Inscribed polygon within the chord error of the circle
+        let exact = 2.0 * PI;
+        let v = volume(&mesh);
+        assert!(v < exact && exact - v < 2.0 * PI * 2.0 * settings.max_chord_error);
+
+        let round_trip = Brep::from_mesh(&mesh).unwrap();
+        round_trip.validate().unwrap();
+        assert_eq!(round_trip.genus().unwrap(), 0);
+    }
+}
@@ -0,0 +1,437 @@
+//! Direct modeling on B-reps
+//!
+//! [`push_pull`] offsets a planar face of a solid along its normal with no
+//! feature history to replay, for quick edits to imported models. The face
+//! takes along every face smoothly joined to it: coplanar pieces, such as
+//! the triangles of a solid built from a mesh, and tangent fillets and
+//! blends, so the tangency across those edges is kept. The faces around
+//! that region keep their surfaces, and the vertices where they meet it
+//! slide along them to follow.
+//!
+//! Tangent faces that can't follow without leaving their neighbors' surfaces
+//! are left behind, which breaks tangency there. The edit fails, leaving
+//! the solid untouched, when an edge would collapse or a vertex would have
+//! to slide along a curved side face other than along a cylinder's axis.
+//! It doesn't look for the moved faces running into distant parts of the
+//! solid.
+
+use super::brep::{Brep, BrepError, BrepResult, EdgeCurve, EdgeId, FaceId, FaceSurface, VertexId};
+use super::intersection::{AnalyticSurface, Frame};
+use super::topology::newell_normal;
+use crate::core::{Point3, Vector3, EPSILON};
+use std::collections::{BTreeSet, HashMap};
+
+/// Distance below which points coincide, and sine of the angle below which
+/// directions are parallel
+const TOLERANCE: f64 = 1e-6;
+
+/// Offset a planar face and the faces smoothly joined to it by `distance`
+/// along its outward normal, pulling out for positive distances and
+/// pushing in for negative ones
+///
+/// Returns the faces that moved, the given one among them.
+pub fn push_pull(brep: &mut Brep, face: FaceId, distance: f64) -> BrepResult<Vec<FaceId>> {
+    let (origin, normal) =
+        face_plane(brep, face)?.ok_or(BrepError::Precondition("push/pull needs a planar face"))?;
+    let adjacency = Adjacency::new(brep)?;
+    let region = adjacency.region(brep, face, &origin, &normal)?;
+    let offset = normal * distance;
+
+    // Where each vertex of the region goes
+    let mut moves = HashMap::new();
+    for &f in &region {
+        let planar = face_plane(brep, f)?.is_some();
+        for v in face_vertices(brep, f)? {
+            if moves.contains_key(&v) {
+                continue;
+            }
+            let point = brep.vertex(v)?.point;
+            let outside: Vec<FaceId> = adjacency.faces_at(v).filter(|g| !region.contains(g)).collect();
+            let step = slide(brep, &outside, &point, &normal, distance)?;
+            if !planar && (step - offset).norm() > TOLERANCE {
+                return Err(BrepError::Precondition("a tangent face can't follow the push/pull"));
+            }
+            for g in outside {
+                check_slide(brep, g, &point, &step)?;
+            }
+            moves.insert(v, step);
+        }
+    }
+
+    // Edges keep their shape where both ends move alike; lines stretch
+    let mut curves = Vec::new();
+    for (&edge, &(a, b)) in &adjacency.edge_vertices {
+        let (Some(da), Some(db)) = (moves.get(&a), moves.get(&b)) else {
+            if moves.contains_key(&a) || moves.contains_key(&b) {
+                stretch(brep, edge, a, b, &moves)?;
+            }
+            continue;
+        };
+        if (da - db).norm() <= TOLERANCE {
+            curves.push((edge, brep.edge(edge)?.curve.translated(da)));
+        } else {
+            stretch(brep, edge, a, b, &moves)?;
+        }
+    }
+
+    for (&v, step) in &moves {
+        let point = brep.vertex(v)?.point + step;
+        brep.set_vertex_point(v, point)?;
+    }
+    for (edge, curve) in curves {
+        brep.set_edge_curve(edge, curve)?;
+    }
+    for &f in &region {
+        let surface = brep.face(f)?.surface.as_ref().map(|s| s.translated(&offset));
+        brep.set_face_surface(f, surface)?;
+    }
+    Ok(region.into_iter().collect())
+}
+
+/// Faces and vertices around each edge and vertex
+struct Adjacency {
+    edge_faces: HashMap<EdgeId, [FaceId; 2]>,
+    edge_vertices: HashMap<EdgeId, (VertexId, VertexId)>,
+    vertex_edges: HashMap<VertexId, Vec<EdgeId>>,
+}
+
+impl Adjacency {
+    fn new(brep: &Brep) -> BrepResult<Self> {
+        let mut adjacency = Self {
+            edge_faces: HashMap::new(),
+            edge_vertices: HashMap::new(),
+            vertex_edges: HashMap::new(),
+        };
+        for edge in brep.edge_ids() {
+            let [first, second] = brep.edge(edge)?.coedges;
+            let face_of =
+                |coedge| -> BrepResult<FaceId> { Ok(brep.face_loop(brep.coedge(coedge)?.loop_id)?.face) };
+            adjacency.edge_faces.insert(edge, [face_of(first)?, face_of(second)?]);
+            let (a, b) = brep.edge_vertices(edge)?;
+            adjacency.edge_vertices.insert(edge, (a, b));
+            adjacency.vertex_edges.entry(a).or_default().push(edge);
+            if b != a {
+                adjacency.vertex_edges.entry(b).or_default().push(edge);
+            }
+        }
+        Ok(adjacency)
+    }
+
+    /// Faces meeting at a vertex
+    fn faces_at(&self, vertex: VertexId) -> impl Iterator<Item = FaceId> + '_ {
+        let faces: BTreeSet<FaceId> = self
+            .vertex_edges
+            .get(&vertex)
+            .into_iter()
+            .flatten()
+            .flat_map(|edge| self.edge_faces[edge])
+            .collect();
+        faces.into_iter()
+    }
+
+    /// `face` and the faces smoothly joined to it that can move with it
+    fn region(
+        &self,
+        brep: &Brep,
+        face: FaceId,
+        origin: &Point3,
+        normal: &Vector3,
+    ) -> BrepResult<BTreeSet<FaceId>> {
+        // Curved faces only follow when their neighbors let them move
+        // straight along the normal; grow again without any that can't
+        let mut excluded = BTreeSet::new();
+        loop {
+            let region = self.grow(brep, face, origin, normal, &excluded)?;
+            match self.stuck_face(brep, face, &region, normal)? {
+                Some(stuck) => excluded.insert(stuck),
+                None => return Ok(region),
+            };
+        }
+    }
+
+    /// Faces reached from `face` across smooth joins, avoiding `excluded`
+    fn grow(
+        &self,
+        brep: &Brep,
+        face: FaceId,
+        origin: &Point3,
+        normal: &Vector3,
+        excluded: &BTreeSet<FaceId>,
+    ) -> BrepResult<BTreeSet<FaceId>> {
+        let mut region = BTreeSet::from([face]);
+        let mut queue = vec![face];
+        while let Some(f) = queue.pop() {
+            for (&edge, &[a, b]) in &self.edge_faces {
+                let other = match (a == f, b == f) {
+                    (true, false) => b,
+                    (false, true) => a,
+                    _ => continue,
+                };
+                if region.contains(&other) || excluded.contains(&other) {
+                    continue;
+                }
+                if self.joins(brep, edge, f, other, origin, normal)? {
+                    region.insert(other);
+                    queue.push(other);
+                }
+            }
+        }
+        Ok(region)
+    }
+
+    /// A curved face of the region held by a neighbor outside it that is
+    /// not parallel to the normal
+    fn stuck_face(
+        &self,
+        brep: &Brep,
+        face: FaceId,
+        region: &BTreeSet<FaceId>,
+        normal: &Vector3,
+    ) -> BrepResult<Option<FaceId>> {
+        for &f in region {
+            if f == face || face_plane(brep, f)?.is_some() {
+                continue;
+            }
+            for v in face_vertices(brep, f)? {
+                let point = brep.vertex(v)?.point;
+                for g in self.faces_at(v).filter(|g| !region.contains(g)) {
+                    if normal_at(brep, g, &point)?.is_none_or(|m| m.dot(normal).abs() > TOLERANCE) {
+                        return Ok(Some(f));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether `other` joins `face` smoothly across `edge`: a plane lying
+    /// in the pushed face's plane, or a curved face tangent to `face`
+    fn joins(
+        &self,
+        brep: &Brep,
+        edge: EdgeId,
+        face: FaceId,
+        other: FaceId,
+        origin: &Point3,
+        normal: &Vector3,
+    ) -> BrepResult<bool> {
+        if let Some((point, other_normal)) = face_plane(brep, other)? {
+            let parallel = other_normal.cross(normal).norm() <= TOLERANCE;
+            return Ok(parallel && (point - origin).dot(normal).abs() <= TOLERANCE);
+        }
+        let (a, b) = self.edge_vertices[&edge];
+        let (start, end) = (brep.vertex(a)?.point, brep.vertex(b)?.point);
+        let sample = match brep.edge(edge)?.curve {
+            EdgeCurve::Line => Point3::from((start.coords + end.coords) / 2.0),
+            _ => start,
+        };
+        Ok(match (normal_at(brep, face, &sample)?, normal_at(brep, other, &sample)?) {
+            (Some(n), Some(m)) => n.cross(&m).norm() <= TOLERANCE,
+            _ => false,
+        })
+    }
+}
+
+/// Smallest move of a vertex that puts it `distance` along `normal` while
+/// keeping it on the tangent planes of the `outside` faces around it
+fn slide(
+    brep: &Brep,
+    outside: &[FaceId],
+    point: &Point3,
+    normal: &Vector3,
+    distance: f64,
+) -> BrepResult<Vector3> {
+    let mut basis: Vec<Vector3> = Vec::new();
+    for &g in outside {
+        let m = normal_at(brep, g, point)?
+            .ok_or(BrepError::Precondition("push/pull can't slide along free-form faces"))?;
+        let residual = basis.iter().fold(m, |m, q| m - q * q.dot(&m));
+        if let Some(q) = residual.try_normalize(TOLERANCE) {
+            basis.push(q);
+        }
+    }
+    let free = basis.iter().fold(*normal, |w, q| w - q * q.dot(&w));
+    let reach = free.dot(normal);
+    if reach <= TOLERANCE {
+        return Err(BrepError::Precondition("the faces around the pushed face hold it in place"));
+    }
+    Ok(free * (distance / reach))
+}
+
+/// Check a vertex moving by `step` stays on side face `face`
+fn check_slide(brep: &Brep, face: FaceId, point: &Point3, step: &Vector3) -> BrepResult<()> {
+    if step.norm() <= TOLERANCE || face_plane(brep, face)?.is_some() {
+        return Ok(());
+    }
+    match &brep.face(face)?.surface {
+        Some(FaceSurface::Analytic(AnalyticSurface::Cylinder { frame, .. }))
+            if step.cross(&frame.z_axis).norm() <= TOLERANCE * step.norm() =>
+        {
+            Ok(())
+        }
+        _ => Err(BrepError::Inconsistent(format!(
+            "vertex at ({:.3}, {:.3}, {:.3}) would leave curved face {}",
+            point.x, point.y, point.z, face.0
+        ))),
+    }
+}
+
+/// Check a straight edge with ends moving apart stays a proper edge
+fn stretch(
+    brep: &Brep,
+    edge: EdgeId,
+    a: VertexId,
+    b: VertexId,
+    moves: &HashMap<VertexId, Vector3>,
+) -> BrepResult<()> {
+    if !matches!(brep.edge(edge)?.curve, EdgeCurve::Line) {
+        return Err(BrepError::Precondition("push/pull would bend a curved edge"));
+    }
+    let zero = Vector3::zeros();
+    let before = brep.vertex(b)?.point - brep.vertex(a)?.point;
+    let after = before + moves.get(&b).unwrap_or(&zero) - moves.get(&a).unwrap_or(&zero);
+    if after.norm() <= TOLERANCE || after.dot(&before) <= 0.0 {
+        return Err(BrepError::Precondition("push/pull would collapse an edge"));
+    }
+    Ok(())
+}
+
+/// Point on a face and its outward normal, if the face is flat
+fn face_plane(brep: &Brep, face: FaceId) -> BrepResult<Option<(Point3, Vector3)>> {
+    let points = outer_points(brep, face)?;
+    let newell = newell_normal(&points).try_normalize(EPSILON);
+    Ok(match &brep.face(face)?.surface {
+        Some(FaceSurface::Plane { origin, normal }) => normal.try_normalize(EPSILON).map(|n| (*origin, n)),
+        Some(FaceSurface::Analytic(AnalyticSurface::Plane { frame, .. })) => {
+            Some((frame.origin, newell.unwrap_or(frame.z_axis)))
+        }
+        None => newell
+            .filter(|n| points.iter().all(|p| (p - points[0]).dot(n).abs() <= TOLERANCE))
+            .map(|n| (points[0], n)),
+        Some(_) => None,
+    })
+}
+
+/// Direction of a face's normal at a point on it, either way round; `None`
+/// for free-form faces
+fn normal_at(brep: &Brep, face: FaceId, point: &Point3) -> BrepResult<Option<Vector3>> {
+    Ok(match &brep.face(face)?.surface {
+        Some(FaceSurface::Plane { normal, .. }) => normal.try_normalize(EPSILON),
+        Some(FaceSurface::Analytic(surface)) => analytic_normal(surface, point),
+        Some(FaceSurface::Nurbs(_)) => None,
+        None => newell_normal(&outer_points(brep, face)?).try_normalize(EPSILON),
+    })
+}
+
+fn analytic_normal(surface: &AnalyticSurface, point: &Point3) -> Option<Vector3> {
+    let radial = |frame: &Frame| {
+        let w = point - frame.origin;
+        (w - frame.z_axis * w.dot(&frame.z_axis)).try_normalize(EPSILON)
+    };
+    match surface {
+        AnalyticSurface::Plane { frame, .. } => Some(frame.z_axis),
+        AnalyticSurface::Cylinder { frame, .. } => radial(frame),
+        AnalyticSurface::Cone { frame, half_angle, .. } => {
+            radial(frame).map(|r| r * half_angle.cos() - frame.z_axis * half_angle.sin())
+        }
+        AnalyticSurface::Sphere { frame, .. } => (point - frame.origin).try_normalize(EPSILON),
+        AnalyticSurface::Torus { frame, major_radius, .. } => {
+            radial(frame).and_then(|r| (point - (frame.origin + r * *major_radius)).try_normalize(EPSILON))
+        }
+    }
+}
+
+fn outer_points(brep: &Brep, face: FaceId) -> BrepResult<Vec<Point3>> {
+    let mut points = Vec::new();
+    for coedge in brep.loop_coedges(brep.face(face)?.outer)? {
+        points.push(brep.vertex(brep.coedge(coedge)?.vertex)?.point);
+    }
+    Ok(points)
+}
+
+fn face_vertices(brep: &Brep, face: FaceId) -> BrepResult<Vec<VertexId>> {
+    let face = brep.face(face)?;
+    let mut vertices = Vec::new();
+    for loop_id in std::iter::once(face.outer).chain(face.rings.iter().copied()) {
+        for coedge in brep.loop_coedges(loop_id)? {
+            vertices.push(brep.coedge(coedge)?.vertex);
+        }
+    }
+    Ok(vertices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine3d::boolean::MeshValidity;
+    use crate::engine3d::tessellation::TessellationSettings;
+    use crate::engine3d::mesh::HalfEdgeMesh;
+    use crate::engine3d::topology::ProfileRegion;
+
+    fn volume(brep: &Brep) -> f64 {
+        let mesh = brep.to_mesh(&TessellationSettings::default()).unwrap();
+        MeshValidity::check(&mesh).unwrap().volume
+    }
+
+    fn top_face(brep: &Brep) -> FaceId {
+        let up = Vector3::new(0.0, 0.0, 1.0);
+        brep.face_ids()
+            .into_iter()
+            .find(|&f| face_plane(brep, f).unwrap().is_some_and(|(_, n)| n.dot(&up) > 0.99))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_push_pull() {
+        // A 2 x 2 x 1 block with a 1 x 1 hole, pulled up one unit
+        let square = |x: f64, size: f64| {
+            vec![
+                Point3::new(x, x, 0.0),
+                Point3::new(x + size, x, 0.0),
+                Point3::new(x + size, x + size, 0.0),
+                Point3::new(x, x + size, 0.0),
+            ]
+        };
+        let region = ProfileRegion::new(square(0.0, 2.0)).with_hole(square(0.5, 1.0));
+        let mut block = Brep::extrude(&region, Vector3::new(0.0, 0.0, 1.0)).unwrap();
+        assert!((volume(&block) - 3.0).abs() < 1e-9);
+        let top = top_face(&block);
+        assert_eq!(push_pull(&mut block, top, 1.0).unwrap(), vec![top]);
+        block.validate().unwrap();
+        assert!((volume(&block) - 6.0).abs() < 1e-9);
+        // Pushing through the bottom collapses the side edges
+        assert!(push_pull(&mut block, top, -2.5).is_err());
+        assert!((volume(&block) - 6.0).abs() < 1e-9);
+
+        // A triangulated import, 4 x 2 x 2 with its right wall drafted in to
+        // x = 3 at the top: both halves of the top move, sliding along the
+        // draft
+        let corners = [
+            (0.0, 0.0, 0.0), (4.0, 0.0, 0.0), (4.0, 2.0, 0.0), (0.0, 2.0, 0.0),
+            (0.0, 0.0, 2.0), (3.0, 0.0, 2.0), (3.0, 2.0, 2.0), (0.0, 2.0, 2.0),
+        ];
+        let mut mesh = HalfEdgeMesh::new();
+        let vertices: Vec<_> =
+            corners.iter().map(|&(x, y, z)| mesh.add_vertex(Point3::new(x, y, z))).collect();
+        let quads = [[0, 3, 2, 1], [4, 5, 6, 7], [0, 1, 5, 4], [3, 7, 6, 2], [0, 4, 7, 3], [1, 2, 6, 5]];
+        for [a, b, c, d] in quads {
+            mesh.add_face(&[vertices[a], vertices[b], vertices[c]]).unwrap();
+            mesh.add_face(&[vertices[a], vertices[c], vertices[d]]).unwrap();
+        }
+        let mut solid = Brep::from_mesh(&mesh).unwrap();
+        assert!((volume(&solid) - 14.0).abs() < 1e-9);
+        let top = top_face(&solid);
+        let moved = push_pull(&mut solid, top, 1.0).unwrap();
+        assert_eq!(moved.len(), 2);
+        solid.validate().unwrap();
+        // The top is 2.5 long at z = 3
+        assert!((volume(&solid) - 14.0 - (3.0 + 2.5) / 2.0 * 2.0).abs() < 1e-9);
+
+        // A cylinder's end slides along its axis
+        let mut cylinder = Brep::cylinder(Point3::origin(), Vector3::new(0.0, 0.0, 1.0), 1.0, 2.0).unwrap();
+        let before = volume(&cylinder);
+        let top = top_face(&cylinder);
+        push_pull(&mut cylinder, top, 1.0).unwrap();
+        assert!((volume(&cylinder) / before - 1.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,301 @@
+//! Draft angle and undercut analysis for molded parts
+//!
+//! A face's draft is the angle between it and the pull direction, measured
+//! as the elevation of its outward normal above the plane normal to the
+//! pull: walls parallel to the pull have no draft, faces square to it have
+//! ±90°. Faces with positive draft release as the cavity half moves along
+//! the pull, faces with negative draft as the core half moves against it,
+//! and faces within the minimum draft of zero drag on the mold.
+//!
+//! A face is an undercut when the part itself blocks its release: a ray
+//! shot from the face in its release direction hits another face. Each
+//! triangle of a face is tested at its centroid, so undercuts narrower than
+//! a triangle can be missed.
+
+use super::analysis::AnalysisError;
+use super::mesh::{FaceHandle, HalfEdgeMesh};
+use super::topology::newell_normal;
+use crate::core::{Point3, Vector3, EPSILON};
+use crate::geometry::point::Point2D;
+use crate::geometry::tessellate::tessellate;
+use serde::{Deserialize, Serialize};
+
+/// Draft analysis settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DraftSettings {
+    /// Direction the cavity half of the mold opens in
+    pub pull: Vector3,
+    /// Smallest draft, in radians, a face needs to release cleanly
+    pub min_draft: f64,
+    /// Test faces for undercuts by ray casting
+    pub detect_undercuts: bool,
+}
+
+impl Default for DraftSettings {
+    fn default() -> Self {
+        Self {
+            pull: Vector3::new(0.0, 0.0, 1.0),
+            min_draft: 1f64.to_radians(),
+            detect_undercuts: true,
+        }
+    }
+}
+
+/// How a face releases from the mold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DraftClass {
+    /// Enough draft to release along the pull
+    Positive,
+    /// Enough draft to release against the pull
+    Negative,
+    /// Less than the minimum draft either way
+    Insufficient,
+    /// Release is blocked by another part of the solid
+    Undercut,
+}
+
+/// Draft of one face
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaceDraft {
+    /// Analysed face
+    pub face: FaceHandle,
+    /// Signed draft angle in radians, positive when facing along the pull
+    pub angle: f64,
+    /// Unit outward normal
+    pub normal: Vector3,
+    /// Face area
+    pub area: f64,
+    /// Release classification
+    pub class: DraftClass,
+    /// Triangulation of the face, for display
+    pub triangles: Vec<[Point3; 3]>,
+}
+
+/// Draft angles and undercuts of every face of a solid
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DraftAnalysis {
+    /// Unit pull direction
+    pub pull: Vector3,
+    /// Minimum draft in radians
+    pub min_draft: f64,
+    /// Per-face results in face order
+    pub faces: Vec<FaceDraft>,
+}
+
+impl DraftAnalysis {
+    /// Analyse a closed solid with outward faces
+    pub fn analyze(mesh: &HalfEdgeMesh, settings: &DraftSettings) -> Result<Self, AnalysisError> {
+        let pull = settings.pull.try_normalize(EPSILON).ok_or(AnalysisError::ZeroDirection)?;
+        let min_draft = settings.min_draft.abs();
+
+        let mut faces = Vec::new();
+        for face in mesh.face_handles() {
+            let points = mesh
+                .face_vertices(face)
+                .map_err(|_| AnalysisError::InvalidMesh)?
+                .into_iter()
+                .map(|v| mesh.get_vertex(v).map(|v| v.position))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| AnalysisError::InvalidMesh)?;
+            let normal = newell_normal(&points);
+            let Some(unit) = normal.try_normalize(EPSILON) else {
+                continue;
+            };
+
+            let angle = unit.dot(&pull).clamp(-1.0, 1.0).asin();
+            let class = if angle >= min_draft {
+                DraftClass::Positive
+            } else if angle <= -min_draft {
+                DraftClass::Negative
+            } else {
+                DraftClass::Insufficient
+            };
+            faces.push(FaceDraft {
+                face,
+                angle,
+                normal: unit,
+                area: normal.norm() / 2.0,
+                class,
+                triangles: triangulate(&points, &unit),
+            });
+        }
+
+        let mut analysis = Self { pull, min_draft, faces };
+        if settings.detect_undercuts {
+            analysis.mark_undercuts();
+        }
+        Ok(analysis)
+    }
+
+    /// Result for a face
+    pub fn face(&self, face: FaceHandle) -> Option<&FaceDraft> {
+        self.faces.iter().find(|f| f.face == face)
+    }
+
+    /// Faces of a class
+    pub fn faces_of(&self, class: DraftClass) -> impl Iterator<Item = &FaceDraft> {
+        self.faces.iter().filter(move |f| f.class == class)
+    }
+
+    /// Whether any face is an undercut
+    pub fn has_undercuts(&self) -> bool {
+        self.faces_of(DraftClass::Undercut).next().is_some()
+    }
+
+    /// Total area of the faces of a class
+    pub fn area(&self, class: DraftClass) -> f64 {
+        self.faces_of(class).map(|f| f.area).sum()
+    }
+
+    /// Smallest and largest draft in radians, `None` without faces
+    pub fn angle_range(&self) -> Option<(f64, f64)> {
+        let mut angles = self.faces.iter().map(|f| f.angle);
+        let first = angles.next()?;
+        Some(angles.fold((first, first), |(lo, hi), a| (lo.min(a), hi.max(a))))
+    }
+
+    /// Reclassify faces whose release ray hits another face
+    fn mark_undercuts(&mut self) {
+        let undercuts: Vec<usize> = (0..self.faces.len())
+            .filter(|&i| {
+                let face = &self.faces[i];
+                let direction = match face.class {
+                    DraftClass::Positive => self.pull,
+                    DraftClass::Negative => -self.pull,
+                    _ => return false,
+                };
+                face.triangles.iter().any(|[a, b, c]| {
+                    let origin = Point3::from((a.coords + b.coords + c.coords) / 3.0);
+                    self.faces.iter().enumerate().any(|(j, other)| {
+                        j != i
+                            && other
+                                .triangles
+                                .iter()
+                                .any(|t| ray_hits(&origin, &direction, t))
+                    })
+                })
+            })
+            .collect();
+        for i in undercuts {
+            self.faces[i].class = DraftClass::Undercut;
+        }
+    }
+}
+
+/// Triangles of a planar polygon, concave ones included
+fn triangulate(points: &[Point3], normal: &Vector3) -> Vec<[Point3; 3]> {
+    if points.len() == 3 {
+        return vec![[points[0], points[1], points[2]]];
+    }
+    let seed = if normal.x.abs() < 0.9 { Vector3::x() } else { Vector3::y() };
+    let u = (seed - normal * seed.dot(normal)).normalize();
+    let v = normal.cross(&u);
+    let flat: Vec<Point2D> = points
+        .iter()
+        .map(|p| Point2D::new(p.coords.dot(&u), p.coords.dot(&v)))
+        .collect();
+    tessellate(&flat, &[])
+        .indices
+        .chunks_exact(3)
+        .map(|t| [points[t[0] as usize], points[t[1] as usize], points[t[2] as usize]])
+        .collect()
+}
+
+/// Whether a ray strictly in front of its origin crosses a triangle
+/// (Möller-Trumbore)
+fn ray_hits(origin: &Point3, direction: &Vector3, [a, b, c]: &[Point3; 3]) -> bool {
+    let (e1, e2) = (b - a, c - a);
+    let p = direction.cross(&e2);
+    let det = e1.dot(&p);
+    if det.abs() < EPSILON {
+        return false;
+    }
+    let s = origin - a;
+    let u = s.dot(&p) / det;
+    let q = s.cross(&e1);
+    let v = direction.dot(&q) / det;
+    u >= 0.0 && v >= 0.0 && u + v <= 1.0 && e2.dot(&q) / det > EPSILON
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine3d::topology::ExtrudeOperation;
+
+    #[test]
+    fn test_drafted_box() {
+        let square = vec![
+            Point3::new(-5.0, -5.0, 0.0),
+            Point3::new(5.0, -5.0, 0.0),
+            Point3::new(5.0, 5.0, 0.0),
+            Point3::new(-5.0, 5.0, 0.0),
+        ];
+        let mesh = ExtrudeOperation {
+            direction: Vector3::new(0.0, 0.0, 4.0),
+            draft: 3f64.to_radians(),
+            ..Default::default()
+        }
+        .extrude_profile(&square)
+        .unwrap();
+
+        let analysis = DraftAnalysis::analyze(&mesh, &DraftSettings::default()).unwrap();
+        let walls: Vec<_> = analysis.faces.iter().filter(|f| f.angle.abs() < 1.0).collect();
+        assert_eq!(walls.len(), 4);
+        assert!(walls.iter().all(|f| (f.angle.to_degrees() - 3.0).abs() < 1e-9));
+        let (lo, hi) = analysis.angle_range().unwrap();
+        assert!((lo + std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+        assert!((hi - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+        assert_eq!(analysis.faces_of(DraftClass::Negative).count(), 1);
+        assert!(!analysis.has_undercuts());
+
+        // The same walls fall short of a 5 degree minimum
+        let strict = DraftSettings {
+            min_draft: 5f64.to_radians(),
+            ..Default::default()
+        };
+        let analysis = DraftAnalysis::analyze(&mesh, &strict).unwrap();
+        assert_eq!(analysis.faces_of(DraftClass::Insufficient).count(), 4);
+
+        let zero = DraftSettings {
+            pull: Vector3::zeros(),
+            ..Default::default()
+        };
+        assert!(matches!(DraftAnalysis::analyze(&mesh, &zero), Err(AnalysisError::ZeroDirection)));
+    }
+
+    #[test]
+    fn test_hook_undercut() {
+        // C-shaped section in the XZ plane, open towards +X, extruded along Y
+        let section = vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(10.0, 0.0, 0.0),
+            Point3::new(10.0, 0.0, 2.0),
+            Point3::new(2.0, 0.0, 2.0),
+            Point3::new(2.0, 0.0, 8.0),
+            Point3::new(10.0, 0.0, 8.0),
+            Point3::new(10.0, 0.0, 10.0),
+            Point3::new(0.0, 0.0, 10.0),
+        ];
+        let mesh = ExtrudeOperation {
+            direction: Vector3::new(0.0, 5.0, 0.0),
+            ..Default::default()
+        }
+        .extrude_profile(&section)
+        .unwrap();
+
+        // Pulled along Z the inner faces of the jaws trap the mold
+        let analysis = DraftAnalysis::analyze(&mesh, &DraftSettings::default()).unwrap();
+        let undercuts: Vec<_> = analysis.faces_of(DraftClass::Undercut).collect();
+        assert_eq!(undercuts.len(), 2);
+        assert!(undercuts.iter().all(|f| (f.area - 40.0).abs() < 1e-9));
+
+        // Pulled along X the opening releases everything
+        let sideways = DraftSettings {
+            pull: Vector3::new(1.0, 0.0, 0.0),
+            ..Default::default()
+        };
+        let analysis = DraftAnalysis::analyze(&mesh, &sideways).unwrap();
+        assert!(!analysis.has_undercuts());
+        assert!((analysis.area(DraftClass::Positive) - 5.0 * (2.0 + 6.0 + 2.0)).abs() < 1e-9);
+    }
+}
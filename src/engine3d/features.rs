@@ -0,0 +1,869 @@
+//! Parametric feature history
+//!
+//! A [`FeatureTree`] records how a part is built as an ordered list of
+//! features: sketches, extrudes, fillets, shells and patterns. Features
+//! refer to earlier features by [`FeatureId`], and the part body is rebuilt
+//! by replaying the active features in order.
+//!
+//! Every edit regenerates the tree. Only the edited feature and the
+//! features downstream of it are recomputed; the rest reuse their cached
+//! results. Features can be edited, renamed, reordered (as long as no feature
+//! moves ahead of something it uses), suppressed, and rolled back to; new
+//! features go in at the rollback marker.
+//!
+//! Feature failures don't stop regeneration: the feature is marked failed,
+//! the features that depend on it are skipped, and the others still build.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use super::boolean::{boolean_operation, BooleanError, BooleanOp};
+use super::mesh::HalfEdgeMesh;
+use super::topology::{faces_facing, ExtrudeOperation, ProfileRegion, ShellOperation, TopologyError};
+use crate::core::{Point3, Vector3, EPSILON};
+use nalgebra::{Rotation3, Unit};
+
+/// Identifier of a feature within its tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FeatureId(pub u64);
+
+impl fmt::Display for FeatureId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// How a pattern repeats its target
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternKind {
+    /// Copies offset by `spacing` each step
+    Linear {
+        /// Offset between consecutive instances
+        spacing: Vector3,
+        /// Instances, including the original
+        count: usize,
+    },
+    /// Copies rotated about an axis, spread evenly over `angle`
+    Circular {
+        /// Point on the axis
+        axis_origin: Point3,
+        /// Axis direction
+        axis_direction: Vector3,
+        /// Instances, including the original
+        count: usize,
+        /// Angle covered in radians; a full turn spaces the instances evenly
+        angle: f64,
+    },
+}
+
+/// What a feature does
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeatureKind {
+    /// Closed profile for later features to use
+    Sketch {
+        /// Profile region
+        region: ProfileRegion,
+    },
+    /// Extrude a sketch and combine it with the body
+    Extrude {
+        /// Sketch to extrude
+        sketch: FeatureId,
+        /// Direction and distance
+        direction: Vector3,
+        /// Draft angle in radians
+        draft: f64,
+        /// How the extrusion combines with the body
+        combine: BooleanOp,
+    },
+    /// Round the side edges of an extrude
+    Fillet {
+        /// Extrude to round
+        target: FeatureId,
+        /// Fillet radius
+        radius: f64,
+        /// Segments per rounded corner
+        segments: usize,
+    },
+    /// Hollow an extrude or fillet to a wall thickness
+    Shell {
+        /// Solid to hollow
+        target: FeatureId,
+        /// Wall thickness
+        thickness: f64,
+        /// Outward directions of the faces left open, e.g. `+Z` for an
+        /// open-topped box
+        open: Vec<Vector3>,
+    },
+    /// Repeat an extrude, fillet or shell
+    Pattern {
+        /// Feature to repeat
+        target: FeatureId,
+        /// Pattern layout
+        kind: PatternKind,
+    },
+}
+
+impl FeatureKind {
+    /// Features this one uses
+    pub fn dependencies(&self) -> Vec<FeatureId> {
+        match self {
+            FeatureKind::Sketch { .. } => Vec::new(),
+            FeatureKind::Extrude { sketch, .. } => vec![*sketch],
+            FeatureKind::Fillet { target, .. }
+            | FeatureKind::Shell { target, .. }
+            | FeatureKind::Pattern { target, .. } => vec![*target],
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            FeatureKind::Sketch { .. } => "Sketch",
+            FeatureKind::Extrude { .. } => "Extrude",
+            FeatureKind::Fillet { .. } => "Fillet",
+            FeatureKind::Shell { .. } => "Shell",
+            FeatureKind::Pattern { .. } => "Pattern",
+        }
+    }
+}
+
+/// A feature in the history
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feature {
+    /// Feature identifier
+    pub id: FeatureId,
+    /// Display name
+    pub name: String,
+    /// Feature definition
+    pub kind: FeatureKind,
+    /// Whether the feature is skipped on regeneration
+    pub suppressed: bool,
+}
+
+/// Outcome of a feature at the last regeneration
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeatureStatus {
+    /// Built successfully
+    Ok,
+    /// Suppressed by the user
+    Suppressed,
+    /// After the rollback marker
+    RolledBack,
+    /// Skipped because a feature it uses is suppressed or failed
+    ParentUnavailable(FeatureId),
+    /// Building the feature failed
+    Failed(String),
+}
+
+/// What a regeneration did
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegenerationReport {
+    /// Features recomputed, in order
+    pub regenerated: Vec<FeatureId>,
+    /// Features that failed, with the reason
+    pub failed: Vec<(FeatureId, String)>,
+}
+
+/// Cached result of a feature
+#[derive(Debug, Clone)]
+enum FeatureOutput {
+    /// A profile (sketch)
+    Profile(ProfileRegion),
+    /// Solids combined into the body, and how
+    Solids(Vec<HalfEdgeMesh>, BooleanOp),
+}
+
+/// Ordered, regenerating feature history of a part
+#[derive(Debug, Clone, Default)]
+pub struct FeatureTree {
+    features: Vec<Feature>,
+    /// Number of active features; `None` when not rolled back
+    rollback: Option<usize>,
+    next_id: u64,
+    outputs: HashMap<FeatureId, FeatureOutput>,
+    status: HashMap<FeatureId, FeatureStatus>,
+    dirty: HashSet<FeatureId>,
+    body: Option<HalfEdgeMesh>,
+    last_report: RegenerationReport,
+}
+
+impl FeatureTree {
+    /// Create an empty tree
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Features in history order
+    pub fn features(&self) -> &[Feature] {
+        &self.features
+    }
+
+    /// Look up a feature
+    pub fn feature(&self, id: FeatureId) -> Option<&Feature> {
+        self.features.iter().find(|f| f.id == id)
+    }
+
+    /// Position of a feature in the history
+    pub fn index_of(&self, id: FeatureId) -> Option<usize> {
+        self.features.iter().position(|f| f.id == id)
+    }
+
+    /// Status of a feature at the last regeneration
+    pub fn status(&self, id: FeatureId) -> Option<&FeatureStatus> {
+        self.status.get(&id)
+    }
+
+    /// The part body, if any solid feature built
+    pub fn body(&self) -> Option<&HalfEdgeMesh> {
+        self.body.as_ref()
+    }
+
+    /// Report of the last regeneration
+    pub fn last_regeneration(&self) -> &RegenerationReport {
+        &self.last_report
+    }
+
+    /// Rollback marker: the number of active features, or `None` for all
+    pub fn rollback_position(&self) -> Option<usize> {
+        self.rollback
+    }
+
+    /// Features that use `id`, directly or through other features
+    pub fn dependents(&self, id: FeatureId) -> Vec<FeatureId> {
+        let mut found: HashSet<FeatureId> = HashSet::from([id]);
+        let mut dependents = Vec::new();
+        for feature in &self.features {
+            if feature.kind.dependencies().iter().any(|d| found.contains(d)) {
+                found.insert(feature.id);
+                dependents.push(feature.id);
+            }
+        }
+        dependents
+    }
+
+    /// Add a feature at the rollback marker (the end when not rolled back)
+    pub fn add(&mut self, name: impl Into<String>, kind: FeatureKind) -> FeatureResult<FeatureId> {
+        let index = self.rollback.unwrap_or(self.features.len());
+        let id = FeatureId(self.next_id + 1);
+        self.check_references(id, &kind, index)?;
+        self.next_id += 1;
+
+        self.features.insert(
+            index,
+            Feature {
+                id,
+                name: name.into(),
+                kind,
+                suppressed: false,
+            },
+        );
+        if let Some(rollback) = &mut self.rollback {
+            *rollback += 1;
+        }
+        self.dirty.insert(id);
+        self.regenerate();
+        Ok(id)
+    }
+
+    /// Change a feature's definition; dependents regenerate with it
+    pub fn edit(&mut self, id: FeatureId, kind: FeatureKind) -> FeatureResult<&RegenerationReport> {
+        let index = self.index_of(id).ok_or(FeatureError::UnknownFeature(id))?;
+        self.check_references(id, &kind, index)?;
+        self.features[index].kind = kind;
+        self.dirty.insert(id);
+        Ok(self.regenerate())
+    }
+
+    /// Rename a feature
+    pub fn rename(&mut self, id: FeatureId, name: impl Into<String>) -> FeatureResult<()> {
+        let index = self.index_of(id).ok_or(FeatureError::UnknownFeature(id))?;
+        self.features[index].name = name.into();
+        Ok(())
+    }
+
+    /// Move a feature to a new position in the history
+    pub fn reorder(&mut self, id: FeatureId, new_index: usize) -> FeatureResult<&RegenerationReport> {
+        let index = self.index_of(id).ok_or(FeatureError::UnknownFeature(id))?;
+        let new_index = new_index.min(self.features.len() - 1);
+        let feature = self.features.remove(index);
+        self.features.insert(new_index, feature);
+
+        let violation = self.features.iter().enumerate().find_map(|(i, f)| {
+            f.kind
+                .dependencies()
+                .into_iter()
+                .find(|d| self.index_of(*d).is_none_or(|di| di >= i))
+                .map(|d| (f.id, d))
+        });
+        if let Some((feature, dependency)) = violation {
+            let moved = self.features.remove(new_index);
+            self.features.insert(index, moved);
+            return Err(FeatureError::OrderViolation { feature, dependency });
+        }
+
+        Ok(self.regenerate())
+    }
+
+    /// Suppress or unsuppress a feature; its dependents follow
+    pub fn suppress(&mut self, id: FeatureId, suppressed: bool) -> FeatureResult<&RegenerationReport> {
+        let index = self.index_of(id).ok_or(FeatureError::UnknownFeature(id))?;
+        self.features[index].suppressed = suppressed;
+        self.dirty.insert(id);
+        Ok(self.regenerate())
+    }
+
+    /// Roll back to just before the feature at `position`, or forward to the
+    /// end with `None`
+    pub fn rollback_to(&mut self, position: Option<usize>) -> &RegenerationReport {
+        self.rollback = position.filter(|&p| p < self.features.len());
+        self.regenerate()
+    }
+
+    /// Remove a feature that nothing depends on
+    pub fn remove(&mut self, id: FeatureId) -> FeatureResult<Feature> {
+        let index = self.index_of(id).ok_or(FeatureError::UnknownFeature(id))?;
+        let dependents = self.dependents(id);
+        if !dependents.is_empty() {
+            return Err(FeatureError::HasDependents { feature: id, dependents });
+        }
+
+        let feature = self.features.remove(index);
+        if let Some(rollback) = &mut self.rollback {
+            if index < *rollback {
+                *rollback -= 1;
+            }
+        }
+        self.outputs.remove(&id);
+        self.status.remove(&id);
+        self.dirty.remove(&id);
+        self.regenerate();
+        Ok(feature)
+    }
+
+    /// Rebuild outdated features and the body
+    ///
+    /// Features that are marked dirty, have no cached result, or use a
+    /// feature that was rebuilt are recomputed; everything else is reused.
+    pub fn regenerate(&mut self) -> &RegenerationReport {
+        let active = self.rollback.unwrap_or(self.features.len());
+        let mut report = RegenerationReport::default();
+        let mut rebuilt: HashSet<FeatureId> = HashSet::new();
+        let mut unavailable: HashSet<FeatureId> = HashSet::new();
+
+        for index in 0..self.features.len() {
+            let feature = self.features[index].clone();
+            let id = feature.id;
+
+            if index >= active {
+                self.status.insert(id, FeatureStatus::RolledBack);
+                continue;
+            }
+            if feature.suppressed {
+                self.status.insert(id, FeatureStatus::Suppressed);
+                unavailable.insert(id);
+                continue;
+            }
+            if let Some(parent) = feature.kind.dependencies().into_iter().find(|d| unavailable.contains(d)) {
+                self.status.insert(id, FeatureStatus::ParentUnavailable(parent));
+                unavailable.insert(id);
+                continue;
+            }
+
+            let stale = self.dirty.contains(&id)
+                || !self.outputs.contains_key(&id)
+                || feature.kind.dependencies().iter().any(|d| rebuilt.contains(d));
+            if !stale {
+                self.status.insert(id, FeatureStatus::Ok);
+                continue;
+            }
+
+            rebuilt.insert(id);
+            report.regenerated.push(id);
+            match self.build(&feature.kind) {
+                Ok(output) => {
+                    self.outputs.insert(id, output);
+                    self.status.insert(id, FeatureStatus::Ok);
+                }
+                Err(error) => {
+                    self.outputs.remove(&id);
+                    self.status.insert(id, FeatureStatus::Failed(error.to_string()));
+                    report.failed.push((id, error.to_string()));
+                    unavailable.insert(id);
+                }
+            }
+        }
+
+        self.dirty.clear();
+        let (body, failed) = self.combine(active, &unavailable);
+        for (id, reason) in failed {
+            self.status.insert(id, FeatureStatus::Failed(reason.clone()));
+            report.failed.push((id, reason));
+        }
+        self.body = body;
+        self.last_report = report;
+        &self.last_report
+    }
+
+    /// Check that every reference exists and comes before `index`
+    fn check_references(&self, id: FeatureId, kind: &FeatureKind, index: usize) -> FeatureResult<()> {
+        for dependency in kind.dependencies() {
+            let position = self.index_of(dependency).ok_or(FeatureError::UnknownFeature(dependency))?;
+            if position >= index || dependency == id {
+                return Err(FeatureError::OrderViolation { feature: id, dependency });
+            }
+            let referenced = &self.features[position].kind;
+            let valid = match kind {
+                FeatureKind::Extrude { .. } => matches!(referenced, FeatureKind::Sketch { .. }),
+                FeatureKind::Fillet { .. } => matches!(referenced, FeatureKind::Extrude { .. }),
+                FeatureKind::Shell { .. } => {
+                    matches!(referenced, FeatureKind::Extrude { .. } | FeatureKind::Fillet { .. })
+                }
+                FeatureKind::Pattern { .. } => matches!(
+                    referenced,
+                    FeatureKind::Extrude { .. } | FeatureKind::Fillet { .. } | FeatureKind::Shell { .. }
+                ),
+                FeatureKind::Sketch { .. } => false,
+            };
+            if !valid {
+                return Err(FeatureError::InvalidReference {
+                    feature: id,
+                    reference: dependency,
+                    expected: match kind {
+                        FeatureKind::Extrude { .. } => "a sketch",
+                        FeatureKind::Fillet { .. } => "an extrude",
+                        FeatureKind::Shell { .. } => "an extrude or fillet",
+                        _ => "an extrude, fillet or shell",
+                    },
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute a feature's result from the cached results it uses
+    fn build(&self, kind: &FeatureKind) -> FeatureResult<FeatureOutput> {
+        match kind {
+            FeatureKind::Sketch { region } => Ok(FeatureOutput::Profile(region.clone())),
+            FeatureKind::Extrude { .. } => self.extrude(kind, None),
+            FeatureKind::Fillet { target, radius, segments } => {
+                if *radius <= 0.0 || *segments == 0 {
+                    return Err(FeatureError::InvalidParameter(format!(
+                        "fillet radius {} with {} segments",
+                        radius, segments
+                    )));
+                }
+                let target = self.feature(*target).ok_or(FeatureError::UnknownFeature(*target))?;
+                self.extrude(&target.kind, Some((*radius, *segments)))
+            }
+            FeatureKind::Shell { target, thickness, open } => {
+                let Some(FeatureOutput::Solids(solids, combine)) = self.outputs.get(target) else {
+                    return Err(FeatureError::UnknownFeature(*target));
+                };
+                let mut shelled = Vec::new();
+                for solid in solids {
+                    let shell = ShellOperation { thickness: *thickness, ..Default::default() }
+                        .with_open_faces(open.iter().flat_map(|direction| faces_facing(solid, direction)));
+                    shelled.push(shell.hollow(solid)?);
+                }
+                Ok(FeatureOutput::Solids(shelled, *combine))
+            }
+            FeatureKind::Pattern { target, kind } => {
+                let Some(FeatureOutput::Solids(solids, combine)) = self.outputs.get(target) else {
+                    return Err(FeatureError::UnknownFeature(*target));
+                };
+                let mut copies = Vec::new();
+                for solid in solids {
+                    copies.extend(pattern_copies(solid, kind)?);
+                }
+                Ok(FeatureOutput::Solids(copies, *combine))
+            }
+        }
+    }
+
+    /// Solid of an extrude, optionally with its side edges rounded
+    fn extrude(&self, kind: &FeatureKind, fillet: Option<(f64, usize)>) -> FeatureResult<FeatureOutput> {
+        let FeatureKind::Extrude { sketch, direction, draft, combine } = kind else {
+            return Err(FeatureError::InvalidParameter("fillet target is not an extrude".to_string()));
+        };
+        let Some(FeatureOutput::Profile(region)) = self.outputs.get(sketch) else {
+            return Err(FeatureError::UnknownFeature(*sketch));
+        };
+
+        let region = match fillet {
+            Some((radius, segments)) => {
+                let axis = direction.normalize();
+                ProfileRegion {
+                    outer: fillet_ring(&region.outer, &axis, radius, segments),
+                    holes: region.holes.iter().map(|h| fillet_ring(h, &axis, radius, segments)).collect(),
+                }
+            }
+            None => region.clone(),
+        };
+
+        let operation = ExtrudeOperation {
+            direction: *direction,
+            draft: *draft,
+            ..Default::default()
+        };
+        Ok(FeatureOutput::Solids(vec![operation.extrude_region(&region)?], *combine))
+    }
+
+    /// Combine the solids of the active features into the body
+    ///
+    /// A fillet stands in for the extrude it rounds; patterns add their
+    /// copies alongside the original. A feature whose solid can't be
+    /// combined is left out of the body and reported as failed.
+    fn combine(
+        &self,
+        active: usize,
+        unavailable: &HashSet<FeatureId>,
+    ) -> (Option<HalfEdgeMesh>, Vec<(FeatureId, String)>) {
+        let mut contributions: Vec<(FeatureId, &Vec<HalfEdgeMesh>, BooleanOp)> = Vec::new();
+        for feature in &self.features[..active] {
+            if unavailable.contains(&feature.id) {
+                continue;
+            }
+            let Some(FeatureOutput::Solids(solids, combine)) = self.outputs.get(&feature.id) else {
+                continue;
+            };
+            match &feature.kind {
+                FeatureKind::Fillet { target, .. } | FeatureKind::Shell { target, .. } => {
+                    match contributions.iter_mut().find(|(id, _, _)| id == target) {
+                        Some(entry) => *entry = (feature.id, solids, *combine),
+                        None => contributions.push((feature.id, solids, *combine)),
+                    }
+                }
+                _ => contributions.push((feature.id, solids, *combine)),
+            }
+        }
+
+        let mut body: Option<HalfEdgeMesh> = None;
+        let mut failed = Vec::new();
+        for (id, solids, combine) in contributions {
+            let mut result = body.clone();
+            let mut error = None;
+            for solid in solids {
+                result = match &result {
+                    None if combine == BooleanOp::Union => Some(solid.clone()),
+                    None => None,
+                    Some(current) => match boolean_operation(current, solid, combine) {
+                        Ok(combined) => Some(combined),
+                        Err(e) => {
+                            error = Some(e.to_string());
+                            break;
+                        }
+                    },
+                };
+            }
+            match error {
+                Some(reason) => failed.push((id, reason)),
+                None => body = result,
+            }
+        }
+        (body, failed)
+    }
+}
+
+impl fmt::Display for FeatureTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, feature) in self.features.iter().enumerate() {
+            if self.rollback == Some(index) {
+                writeln!(f, "-- rollback --")?;
+            }
+            let status = match self.status.get(&feature.id) {
+                Some(FeatureStatus::Ok) | None => String::new(),
+                Some(FeatureStatus::Suppressed) => " (suppressed)".to_string(),
+                Some(FeatureStatus::RolledBack) => " (rolled back)".to_string(),
+                Some(FeatureStatus::ParentUnavailable(parent)) => format!(" (needs {})", parent),
+                Some(FeatureStatus::Failed(reason)) => format!(" (failed: {})", reason),
+            };
+            writeln!(f, "{} {} {}{}", feature.id, feature.kind.type_name(), feature.name, status)?;
+        }
+        Ok(())
+    }
+}
+
+/// Copies of a solid for a pattern, excluding the original
+fn pattern_copies(solid: &HalfEdgeMesh, kind: &PatternKind) -> FeatureResult<Vec<HalfEdgeMesh>> {
+    match kind {
+        PatternKind::Linear { spacing, count } => {
+            if *count < 2 {
+                return Err(FeatureError::InvalidParameter("pattern needs at least 2 instances".to_string()));
+            }
+            (1..*count)
+                .map(|i| transformed(solid, |p| p + spacing * i as f64))
+                .collect()
+        }
+        PatternKind::Circular {
+            axis_origin,
+            axis_direction,
+            count,
+            angle,
+        } => {
+            if *count < 2 || axis_direction.norm() < EPSILON {
+                return Err(FeatureError::InvalidParameter(
+                    "circular pattern needs an axis and at least 2 instances".to_string(),
+                ));
+            }
+            let full = angle.abs() >= 2.0 * std::f64::consts::PI - EPSILON;
+            let step = if full { angle / *count as f64 } else { angle / (*count - 1) as f64 };
+            let axis = Unit::new_normalize(*axis_direction);
+            (1..*count)
+                .map(|i| {
+                    let rotation = Rotation3::from_axis_angle(&axis, step * i as f64);
+                    transformed(solid, |p| axis_origin + rotation * (p - axis_origin))
+                })
+                .collect()
+        }
+    }
+}
+
+/// A copy of a mesh with every vertex mapped
+fn transformed(mesh: &HalfEdgeMesh, map: impl Fn(Point3) -> Point3) -> FeatureResult<HalfEdgeMesh> {
+    let mut result = HalfEdgeMesh::new();
+    let mut handles = HashMap::new();
+    for vh in mesh.vertex_handles() {
+        let vertex = mesh.get_vertex(vh).map_err(|_| TopologyError::InvalidMesh)?;
+        handles.insert(vh, result.add_vertex(map(vertex.position)));
+    }
+    for fh in mesh.face_handles() {
+        let vertices = mesh.face_vertices(fh).map_err(|_| TopologyError::InvalidMesh)?;
+        let mapped: Vec<_> = vertices.iter().map(|vh| handles[vh]).collect();
+        result.add_face(&mapped).map_err(|_| TopologyError::MeshCreationFailed)?;
+    }
+    result.update_vertex_normals();
+    Ok(result)
+}
+
+/// A loop with every corner rounded, in the plane normal to `axis`
+///
+/// The radius shrinks at corners too tight for it, so neighbouring fillets
+/// never overrun an edge.
+fn fillet_ring(ring: &[Point3], axis: &Vector3, radius: f64, segments: usize) -> Vec<Point3> {
+    let n = ring.len();
+    let mut rounded = Vec::with_capacity(n * (segments + 1));
+    for i in 0..n {
+        let (prev, corner, next) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+        let (to_prev, to_next) = (prev - corner, next - corner);
+        let (a, b) = (to_prev.normalize(), to_next.normalize());
+        let theta = a.dot(&b).clamp(-1.0, 1.0).acos();
+        if theta < EPSILON || (std::f64::consts::PI - theta) < 1e-6 {
+            rounded.push(corner);
+            continue;
+        }
+
+        let half = theta / 2.0;
+        let setback = (radius / half.tan()).min(to_prev.norm() / 2.0).min(to_next.norm() / 2.0);
+        let r = setback * half.tan();
+        let start = corner + a * setback;
+        let end = corner + b * setback;
+        let center = corner + (a + b).normalize() * (r / half.sin());
+        let (u, v) = (start - center, end - center);
+        let sweep = std::f64::consts::PI - theta;
+
+        // Keep points in the sketch plane even if the loop is slightly warped
+        let flatten = |p: Point3| p - axis * (p - corner).dot(axis);
+        for k in 0..=segments {
+            let s = k as f64 / segments as f64;
+            let point = center + (u * ((1.0 - s) * sweep).sin() + v * (s * sweep).sin()) / sweep.sin();
+            rounded.push(flatten(point));
+        }
+    }
+    rounded
+}
+
+/// Feature history errors
+#[derive(Debug, thiserror::Error)]
+pub enum FeatureError {
+    #[error("Unknown feature {0}")]
+    UnknownFeature(FeatureId),
+
+    #[error("Feature {feature} references {reference}, which is not {expected}")]
+    InvalidReference {
+        feature: FeatureId,
+        reference: FeatureId,
+        expected: &'static str,
+    },
+
+    #[error("Feature {feature} would come before {dependency}, which it uses")]
+    OrderViolation { feature: FeatureId, dependency: FeatureId },
+
+    #[error("Feature {feature} is used by {dependents:?}")]
+    HasDependents {
+        feature: FeatureId,
+        dependents: Vec<FeatureId>,
+    },
+
+    #[error("Invalid feature parameter: {0}")]
+    InvalidParameter(String),
+
+    #[error(transparent)]
+    Topology(#[from] TopologyError),
+
+    #[error(transparent)]
+    Boolean(#[from] BooleanError),
+}
+
+/// Result type for feature history operations
+pub type FeatureResult<T> = Result<T, FeatureError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine3d::boolean::MeshValidity;
+
+    fn rectangle(x0: f64, y0: f64, x1: f64, y1: f64) -> ProfileRegion {
+        ProfileRegion::new(vec![
+            Point3::new(x0, y0, 0.0),
+            Point3::new(x1, y0, 0.0),
+            Point3::new(x1, y1, 0.0),
+            Point3::new(x0, y1, 0.0),
+        ])
+    }
+
+    fn extrude(sketch: FeatureId, height: f64, combine: BooleanOp) -> FeatureKind {
+        FeatureKind::Extrude {
+            sketch,
+            direction: Vector3::new(0.0, 0.0, height),
+            draft: 0.0,
+            combine,
+        }
+    }
+
+    fn volume(tree: &FeatureTree) -> f64 {
+        MeshValidity::check(tree.body().unwrap()).unwrap().volume
+    }
+
+    /// Block 4 x 2 x 1 with a 1 x 1 pocket
+    fn block_with_pocket() -> (FeatureTree, [FeatureId; 4]) {
+        let mut tree = FeatureTree::new();
+        let base = tree.add("Base sketch", FeatureKind::Sketch { region: rectangle(0.0, 0.0, 4.0, 2.0) }).unwrap();
+        let block = tree.add("Block", extrude(base, 1.0, BooleanOp::Union)).unwrap();
+        let cut = tree
+            .add("Pocket sketch", FeatureKind::Sketch { region: rectangle(0.5, 0.5, 1.5, 1.5) })
+            .unwrap();
+        let pocket = tree
+            .add(
+                "Pocket",
+                FeatureKind::Extrude {
+                    sketch: cut,
+                    direction: Vector3::new(0.0, 0.0, 3.0),
+                    draft: 0.0,
+                    combine: BooleanOp::Difference,
+                },
+            )
+            .unwrap();
+        (tree, [base, block, cut, pocket])
+    }
+
+    #[test]
+    fn test_edit_regenerates_dependents() {
+        let (mut tree, [base, block, cut, pocket]) = block_with_pocket();
+        assert!((volume(&tree) - 7.0).abs() < 1e-6);
+
+        let report = tree
+            .edit(base, FeatureKind::Sketch { region: rectangle(0.0, 0.0, 5.0, 2.0) })
+            .unwrap()
+            .clone();
+        assert_eq!(report.regenerated, vec![base, block]);
+        assert!(!report.regenerated.contains(&cut) && !report.regenerated.contains(&pocket));
+        assert!((volume(&tree) - 9.0).abs() < 1e-6);
+
+        assert!(matches!(
+            tree.edit(block, extrude(pocket, 1.0, BooleanOp::Union)),
+            Err(FeatureError::OrderViolation { .. })
+        ));
+        assert!(matches!(tree.remove(base), Err(FeatureError::HasDependents { .. })));
+    }
+
+    #[test]
+    fn test_suppress_and_rollback() {
+        let (mut tree, [_, _, cut, pocket]) = block_with_pocket();
+
+        tree.suppress(cut, true).unwrap();
+        assert_eq!(tree.status(pocket), Some(&FeatureStatus::ParentUnavailable(cut)));
+        assert!((volume(&tree) - 8.0).abs() < 1e-6);
+        tree.suppress(cut, false).unwrap();
+        assert!((volume(&tree) - 7.0).abs() < 1e-6);
+
+        // New features go in at the rollback marker
+        tree.rollback_to(Some(2));
+        assert_eq!(tree.status(pocket), Some(&FeatureStatus::RolledBack));
+        assert!((volume(&tree) - 8.0).abs() < 1e-6);
+        let boss = tree.add("Boss sketch", FeatureKind::Sketch { region: rectangle(3.0, 0.5, 3.5, 1.0) }).unwrap();
+        assert_eq!(tree.index_of(boss), Some(2));
+        tree.rollback_to(None);
+        assert!((volume(&tree) - 7.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_reorder_respects_dependencies() {
+        let (mut tree, [base, block, cut, pocket]) = block_with_pocket();
+        assert!(matches!(tree.reorder(block, 0), Err(FeatureError::OrderViolation { .. })));
+        assert_eq!(tree.index_of(block), Some(1));
+
+        tree.reorder(cut, 0).unwrap();
+        assert_eq!(tree.index_of(cut), Some(0));
+        assert_eq!(tree.index_of(base), Some(1));
+        assert_eq!(tree.status(pocket), Some(&FeatureStatus::Ok));
+        assert!((volume(&tree) - 7.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fillet_and_pattern() {
+        let mut tree = FeatureTree::new();
+        let sketch = tree.add("Sketch", FeatureKind::Sketch { region: rectangle(0.0, 0.0, 2.0, 2.0) }).unwrap();
+        let pad = tree.add("Pad", extrude(sketch, 1.0, BooleanOp::Union)).unwrap();
+        let fillet = tree
+            .add(
+                "Round",
+                FeatureKind::Fillet {
+                    target: pad,
+                    radius: 0.5,
+                    segments: 8,
+                },
+            )
+            .unwrap();
+        // Four quarter-round corners of radius 0.5 take (1 - pi/4) off the square
+        let rounded = volume(&tree);
+        assert!((rounded - (4.0 - (1.0 - std::f64::consts::FRAC_PI_4))).abs() < 0.01);
+
+        tree.add(
+            "Row",
+            FeatureKind::Pattern {
+                target: fillet,
+                kind: PatternKind::Linear {
+                    spacing: Vector3::new(3.0, 0.0, 0.0),
+                    count: 3,
+                },
+            },
+        )
+        .unwrap();
+        let validity = MeshValidity::check(tree.body().unwrap()).unwrap();
+        assert!(validity.is_closed());
+        assert!((validity.volume - 3.0 * rounded).abs() < 1e-6);
+
+        assert!(tree.to_string().contains("Pattern Row"));
+    }
+
+    #[test]
+    fn test_shell_with_open_face() {
+        let mut tree = FeatureTree::new();
+        let sketch = tree.add("Sketch", FeatureKind::Sketch { region: rectangle(0.0, 0.0, 4.0, 2.0) }).unwrap();
+        let pad = tree.add("Pad", extrude(sketch, 1.0, BooleanOp::Union)).unwrap();
+        let shell = |thickness| FeatureKind::Shell {
+            target: pad,
+            thickness,
+            open: vec![Vector3::new(0.0, 0.0, 1.0)],
+        };
+        let tray = tree.add("Tray", shell(0.25)).unwrap();
+        assert!((volume(&tree) - (8.0 - 3.5 * 1.5 * 0.75)).abs() < 1e-9);
+
+        // Walls thicker than the part report the faces that collapse
+        tree.edit(tray, shell(1.5)).unwrap();
+        let Some(FeatureStatus::Failed(reason)) = tree.status(tray) else {
+            panic!("shell should fail: {:?}", tree.status(tray));
+        };
+        assert!(reason.contains("collapses"), "{}", reason);
+        assert!((volume(&tree) - 8.0).abs() < 1e-9);
+    }
+}
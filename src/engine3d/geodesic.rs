@@ -0,0 +1,983 @@
+//! Geodesic distance and shortest paths on meshes
+//!
+//! Distances along the surface are computed with the heat method (Crane,
+//! Weischedel and Wardetzky 2013): heat diffused from the sources for a
+//! short time points towards them everywhere, and a Poisson solve recovers
+//! the distance whose gradient follows those directions. Both steps are
+//! sparse symmetric solves on the cotangent Laplacian; their Cholesky
+//! factors are computed once per mesh, so each query is only
+//! back-substitution.
+//!
+//! Shortest paths are traced back from the target down the distance field,
+//! face by face, so they cross triangles in straight lines instead of
+//! following mesh edges - the route a cable or pipe laid on the surface
+//! would take.
+
+use super::mesh::{HalfEdgeMesh, MeshError, VertexHandle};
+use crate::core::{Point3, Vector3, EPSILON};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Barycentric weight below which a corner doesn't count
+const BARYCENTRIC_EPSILON: f64 = 1e-9;
+
+/// Heat method settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeodesicSettings {
+    /// Diffusion time as a multiple of the squared mean edge length; larger
+    /// values give smoother but less accurate distances
+    pub time_factor: f64,
+}
+
+impl Default for GeodesicSettings {
+    fn default() -> Self {
+        Self { time_factor: 1.0 }
+    }
+}
+
+/// A point on a mesh triangle
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SurfacePoint {
+    /// Triangle index
+    pub triangle: usize,
+    /// Weights of the triangle's corners, summing to 1
+    pub barycentric: [f64; 3],
+}
+
+/// Surface distance from the sources to every vertex
+#[derive(Debug, Clone)]
+pub struct DistanceField {
+    distances: Vec<f64>,
+}
+
+impl DistanceField {
+    /// Distance at a vertex; infinite for vertices the sources can't reach
+    pub fn vertex(&self, handle: VertexHandle) -> f64 {
+        self.distances.get(handle.0).copied().unwrap_or(f64::INFINITY)
+    }
+
+    /// Distances by vertex index
+    pub fn values(&self) -> &[f64] {
+        &self.distances
+    }
+}
+
+/// Shortest route along the surface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeodesicPath {
+    /// Points from start to end; consecutive points share a triangle
+    pub points: Vec<Point3>,
+    /// Length along the surface
+    pub length: f64,
+}
+
+/// Geodesic errors
+#[derive(Debug, thiserror::Error)]
+pub enum GeodesicError {
+    #[error("Mesh has no non-degenerate triangles")]
+    EmptyMesh,
+
+    #[error("Triangle references missing vertex {0}")]
+    InvalidVertex(usize),
+
+    #[error("No source points given")]
+    NoSources,
+
+    #[error("Invalid surface point on triangle {0}")]
+    InvalidPoint(usize),
+
+    #[error("Target is not connected to the source")]
+    Unreachable,
+
+    #[error("Laplacian is not positive definite")]
+    NotPositiveDefinite,
+
+    #[error("Path tracing failed: {0}")]
+    PathNotFound(String),
+
+    #[error("Mesh error: {0}")]
+    Mesh(#[from] MeshError),
+}
+
+/// Cholesky factor of a sparse symmetric positive definite matrix
+///
+/// Rows are reordered by reverse Cuthill-McKee to keep the nonzeros near
+/// the diagonal, and each row of the factor is stored from its first
+/// nonzero column to the diagonal.
+#[derive(Debug, Clone)]
+struct Cholesky {
+    /// Original index of each reordered row
+    order: Vec<usize>,
+    /// First stored column of each row
+    first: Vec<usize>,
+    /// Start of each row in `values`, plus the end
+    start: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl Cholesky {
+    /// Factor a matrix given by its entries; rows without entries act as
+    /// identity rows
+    fn factor(n: usize, entries: &HashMap<(usize, usize), f64>) -> Result<Self, GeodesicError> {
+        let mut adjacency = vec![Vec::new(); n];
+        for &(i, j) in entries.keys() {
+            if i != j {
+                adjacency[i].push(j);
+            }
+        }
+        let order = reverse_cuthill_mckee(&adjacency);
+        let mut rank = vec![0; n];
+        for (new, &old) in order.iter().enumerate() {
+            rank[old] = new;
+        }
+
+        let mut first: Vec<usize> = (0..n).collect();
+        for &(i, j) in entries.keys() {
+            let (row, col) = (rank[i], rank[j]);
+            first[row] = first[row].min(col);
+        }
+        let mut start = Vec::with_capacity(n + 1);
+        let mut len = 0;
+        for (row, &col) in first.iter().enumerate() {
+            start.push(len);
+            len += row - col + 1;
+        }
+        start.push(len);
+
+        let mut values = vec![0.0; len];
+        for (&(i, j), &value) in entries {
+            let (row, col) = (rank[i], rank[j]);
+            if col <= row {
+                values[start[row] + col - first[row]] += value;
+            }
+        }
+        for row in 0..n {
+            if values[start[row + 1] - 1] == 0.0 {
+                values[start[row + 1] - 1] = 1.0;
+            }
+        }
+
+        for row in 0..n {
+            for col in first[row]..=row {
+                let from = first[row].max(first[col]);
+                let own = &values[start[row] + from - first[row]..start[row] + col - first[row]];
+                let other = &values[start[col] + from - first[col]..start[col] + col - first[col]];
+                let sum =
+                    values[start[row] + col - first[row]] - own.iter().zip(other).map(|(a, b)| a * b).sum::<f64>();
+                if col < row {
+                    values[start[row] + col - first[row]] = sum / values[start[col + 1] - 1];
+                } else if sum > 0.0 {
+                    values[start[row + 1] - 1] = sum.sqrt();
+                } else {
+                    return Err(GeodesicError::NotPositiveDefinite);
+                }
+            }
+        }
+
+        Ok(Self {
+            order,
+            first,
+            start,
+            values,
+        })
+    }
+
+    fn solve(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.order.len();
+        let mut y: Vec<f64> = self.order.iter().map(|&old| b[old]).collect();
+        for row in 0..n {
+            let factor = &self.values[self.start[row]..self.start[row + 1]];
+            let (diagonal, lower) = factor.split_last().expect("row has a diagonal");
+            let sum: f64 = lower.iter().zip(&y[self.first[row]..row]).map(|(l, y)| l * y).sum();
+            y[row] = (y[row] - sum) / diagonal;
+        }
+        for row in (0..n).rev() {
+            let factor = &self.values[self.start[row]..self.start[row + 1]];
+            let (diagonal, lower) = factor.split_last().expect("row has a diagonal");
+            y[row] /= diagonal;
+            let x = y[row];
+            for (y, l) in y[self.first[row]..row].iter_mut().zip(lower) {
+                *y -= l * x;
+            }
+        }
+
+        let mut x = vec![0.0; n];
+        for (new, &old) in self.order.iter().enumerate() {
+            x[old] = y[new];
+        }
+        x
+    }
+}
+
+/// Heat method solver for one mesh
+///
+/// Building the solver factors the heat and Poisson systems; every distance
+/// query after that reuses the factors.
+#[derive(Debug, Clone)]
+pub struct GeodesicSolver {
+    positions: Vec<Point3>,
+    triangles: Vec<[usize; 3]>,
+    /// Triangle across the edge opposite each corner
+    neighbours: Vec<[Option<usize>; 3]>,
+    /// Triangles around each vertex
+    vertex_triangles: Vec<Vec<usize>>,
+    /// Connected component of each vertex; `usize::MAX` for unused vertices
+    components: Vec<usize>,
+    /// Vertices on an open or non-manifold edge
+    boundary: Vec<bool>,
+    /// One vertex per component, holding the Poisson solve's constant
+    pinned: Vec<usize>,
+    /// Heat flow with insulated boundaries
+    heat: Cholesky,
+    /// Heat flow with cold boundaries, for meshes with a boundary
+    heat_cold_boundary: Option<Cholesky>,
+    /// Cotangent Laplacian with the pinned vertices fixed
+    poisson: Cholesky,
+    mean_edge: f64,
+}
+
+impl GeodesicSolver {
+    /// Build a solver for a half-edge mesh; polygon faces are fanned into
+    /// triangles and vertex indices follow the mesh's vertex handles
+    pub fn new(mesh: &HalfEdgeMesh) -> Result<Self, GeodesicError> {
+        Self::with_settings(mesh, GeodesicSettings::default())
+    }
+
+    /// Build a solver for a half-edge mesh with custom settings
+    pub fn with_settings(mesh: &HalfEdgeMesh, settings: GeodesicSettings) -> Result<Self, GeodesicError> {
+        let positions = mesh
+            .vertices
+            .iter()
+            .map(|v| v.as_ref().map_or(Point3::origin(), |v| v.position))
+            .collect();
+
+        let mut triangles = Vec::new();
+        for face in mesh.face_handles() {
+            let corners = mesh.face_vertices(face)?;
+            for i in 1..corners.len() - 1 {
+                triangles.push([corners[0].0, corners[i].0, corners[i + 1].0]);
+            }
+        }
+
+        Self::from_triangles(positions, triangles, settings)
+    }
+
+    /// Build a solver for an indexed triangle list
+    pub fn from_triangles(
+        positions: Vec<Point3>,
+        triangles: Vec<[usize; 3]>,
+        settings: GeodesicSettings,
+    ) -> Result<Self, GeodesicError> {
+        let n = positions.len();
+        if let Some(&bad) = triangles.iter().flatten().find(|&&v| v >= n) {
+            return Err(GeodesicError::InvalidVertex(bad));
+        }
+
+        // Degenerate triangles have no usable angles
+        let triangles: Vec<[usize; 3]> = triangles
+            .into_iter()
+            .filter(|&[a, b, c]| {
+                a != b
+                    && b != c
+                    && a != c
+                    && triangle_normal(&positions[a], &positions[b], &positions[c]).norm() > EPSILON
+            })
+            .collect();
+        if triangles.is_empty() {
+            return Err(GeodesicError::EmptyMesh);
+        }
+
+        let mut edges: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+        let mut vertex_triangles = vec![Vec::new(); n];
+        let mut entries: HashMap<(usize, usize), f64> = HashMap::new();
+        let mut mass = vec![0.0; n];
+        let mut edge_length = 0.0;
+
+        for (t, tri) in triangles.iter().enumerate() {
+            let area = triangle_normal(&positions[tri[0]], &positions[tri[1]], &positions[tri[2]]).norm() / 2.0;
+            for corner in 0..3 {
+                let (i, j, k) = (tri[corner], tri[(corner + 1) % 3], tri[(corner + 2) % 3]);
+                edges.entry((j.min(k), j.max(k))).or_default().push((t, corner));
+                vertex_triangles[i].push(t);
+                mass[i] += area / 3.0;
+                edge_length += (positions[k] - positions[j]).norm();
+
+                // The angle at i weights the edge opposite it
+                let w = cotangent(&positions[i], &positions[j], &positions[k]) / 2.0;
+                *entries.entry((j, k)).or_default() -= w;
+                *entries.entry((k, j)).or_default() -= w;
+                *entries.entry((j, j)).or_default() += w;
+                *entries.entry((k, k)).or_default() += w;
+            }
+        }
+
+        let mut neighbours = vec![[None; 3]; triangles.len()];
+        let mut boundary = vec![false; n];
+        for (&(a, b), sides) in &edges {
+            // Non-manifold edges are treated as boundary
+            if let [(t0, c0), (t1, c1)] = sides[..] {
+                neighbours[t0][c0] = Some(t1);
+                neighbours[t1][c1] = Some(t0);
+            } else {
+                boundary[a] = true;
+                boundary[b] = true;
+            }
+        }
+
+        let components = components(n, &triangles);
+        let mean_edge = edge_length / (3 * triangles.len()) as f64;
+
+        // Heat operator M + tL
+        let t = settings.time_factor * mean_edge * mean_edge;
+        let mut heat: HashMap<(usize, usize), f64> = entries.iter().map(|(&at, &w)| (at, t * w)).collect();
+        for (i, m) in mass.iter().enumerate().filter(|(_, m)| **m > 0.0) {
+            *heat.entry((i, i)).or_default() += m;
+        }
+        let heat_cold_boundary = if boundary.contains(&true) {
+            let interior = heat.iter().filter(|((i, j), _)| !boundary[*i] && !boundary[*j]);
+            Some(Cholesky::factor(n, &interior.map(|(&at, &w)| (at, w)).collect())?)
+        } else {
+            None
+        };
+
+        // The Laplacian is singular on each component until one value is fixed
+        let mut pinned: Vec<usize> = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        for (v, &component) in components.iter().enumerate() {
+            if component != usize::MAX && seen.insert(component) {
+                pinned.push(v);
+            }
+        }
+        let poisson: HashMap<(usize, usize), f64> = entries
+            .into_iter()
+            .filter(|((i, j), _)| !pinned.contains(i) && !pinned.contains(j))
+            .collect();
+
+        Ok(Self {
+            heat: Cholesky::factor(n, &heat)?,
+            heat_cold_boundary,
+            poisson: Cholesky::factor(n, &poisson)?,
+            positions,
+            triangles,
+            neighbours,
+            vertex_triangles,
+            components,
+            boundary,
+            pinned,
+            mean_edge,
+        })
+    }
+
+    /// Triangles the solver works on
+    pub fn triangles(&self) -> &[[usize; 3]] {
+        &self.triangles
+    }
+
+    /// Mean edge length, the resolution of the distances
+    pub fn mean_edge_length(&self) -> f64 {
+        self.mean_edge
+    }
+
+    /// Position of a surface point
+    pub fn point(&self, at: &SurfacePoint) -> Point3 {
+        let tri = self.triangles[at.triangle];
+        let mut p = Vector3::zeros();
+        for (corner, weight) in tri.iter().zip(at.barycentric) {
+            p += self.positions[*corner].coords * weight;
+        }
+        Point3::from(p)
+    }
+
+    /// Surface point at a vertex, if any triangle uses it
+    pub fn vertex_point(&self, handle: VertexHandle) -> Option<SurfacePoint> {
+        let &triangle = self.vertex_triangles.get(handle.0)?.first()?;
+        let mut barycentric = [0.0; 3];
+        let corner = self.triangles[triangle].iter().position(|&v| v == handle.0)?;
+        barycentric[corner] = 1.0;
+        Some(SurfacePoint { triangle, barycentric })
+    }
+
+    /// Closest point on the surface
+    pub fn closest_point(&self, p: &Point3) -> SurfacePoint {
+        let mut best = (
+            f64::INFINITY,
+            SurfacePoint {
+                triangle: 0,
+                barycentric: [1.0, 0.0, 0.0],
+            },
+        );
+        for (triangle, tri) in self.triangles.iter().enumerate() {
+            let barycentric = closest_on_triangle(
+                p,
+                &self.positions[tri[0]],
+                &self.positions[tri[1]],
+                &self.positions[tri[2]],
+            );
+            let at = SurfacePoint { triangle, barycentric };
+            let distance = (self.point(&at) - p).norm_squared();
+            if distance < best.0 {
+                best = (distance, at);
+            }
+        }
+        best.1
+    }
+
+    /// Distance from the nearest source to every vertex
+    pub fn distance_from(&self, sources: &[SurfacePoint]) -> Result<DistanceField, GeodesicError> {
+        if sources.is_empty() {
+            return Err(GeodesicError::NoSources);
+        }
+        let n = self.positions.len();
+
+        // Heat flow from the sources
+        let mut heat = vec![0.0; n];
+        for source in sources {
+            let tri = self.triangle(source)?;
+            for (corner, weight) in tri.iter().zip(source.barycentric) {
+                heat[*corner] += weight;
+            }
+        }
+        let mut u = self.heat.solve(&heat);
+
+        // Heat that can't flow out of an open boundary bends distances
+        // along it; averaging with a cold-boundary solve cancels most of that
+        if let Some(cold) = &self.heat_cold_boundary {
+            let interior: Vec<f64> = heat
+                .iter()
+                .zip(&self.boundary)
+                .map(|(h, &b)| if b { 0.0 } else { *h })
+                .collect();
+            let cold = cold.solve(&interior);
+            for ((u, c), &b) in u.iter_mut().zip(cold).zip(&self.boundary) {
+                *u = (*u + if b { 0.0 } else { c }) / 2.0;
+            }
+        }
+
+        // Integrated divergence of the normalised flow direction
+        let mut divergence = vec![0.0; n];
+        for tri in &self.triangles {
+            let gradient = self.gradient(tri, &u);
+            let norm = gradient.norm();
+            if norm <= EPSILON * EPSILON {
+                continue;
+            }
+            let x = -gradient / norm;
+            for corner in 0..3 {
+                let (i, j, k) = (tri[corner], tri[(corner + 1) % 3], tri[(corner + 2) % 3]);
+                let (pi, pj, pk) = (&self.positions[i], &self.positions[j], &self.positions[k]);
+                divergence[i] +=
+                    (cotangent(pk, pi, pj) * (pj - pi).dot(&x) + cotangent(pj, pk, pi) * (pk - pi).dot(&x)) / 2.0;
+            }
+        }
+
+        // Distance whose gradient best matches the flow direction
+        let mut rhs: Vec<f64> = divergence.iter().map(|d| -d).collect();
+        for &v in &self.pinned {
+            rhs[v] = 0.0;
+        }
+        let phi = self.poisson.solve(&rhs);
+
+        // Zero at the nearest source of each connected piece
+        let mut offsets: HashMap<usize, f64> = HashMap::new();
+        for source in sources {
+            let tri = self.triangles[source.triangle];
+            let value: f64 = tri.iter().zip(source.barycentric).map(|(v, w)| phi[*v] * w).sum();
+            let offset = offsets.entry(self.components[tri[0]]).or_insert(f64::INFINITY);
+            *offset = offset.min(value);
+        }
+        let distances = phi
+            .iter()
+            .zip(&self.components)
+            .map(|(value, component)| match offsets.get(component) {
+                Some(offset) => (value - offset).max(0.0),
+                None => f64::INFINITY,
+            })
+            .collect();
+
+        Ok(DistanceField { distances })
+    }
+
+    /// Distance from a set of vertices to every vertex
+    pub fn distance_from_vertices(&self, sources: &[VertexHandle]) -> Result<DistanceField, GeodesicError> {
+        let points = sources
+            .iter()
+            .map(|&v| self.vertex_point(v).ok_or(GeodesicError::InvalidVertex(v.0)))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.distance_from(&points)
+    }
+
+    /// Distance at a surface point, interpolated from a distance field
+    pub fn distance_at(&self, field: &DistanceField, at: &SurfacePoint) -> f64 {
+        let tri = self.triangles[at.triangle];
+        tri.iter()
+            .zip(at.barycentric)
+            .map(|(v, w)| field.distances[*v] * w)
+            .sum()
+    }
+
+    /// Shortest path along the surface between two points
+    pub fn shortest_path(&self, from: &SurfacePoint, to: &SurfacePoint) -> Result<GeodesicPath, GeodesicError> {
+        let source = self.triangle(from)?;
+        let target = self.triangle(to)?;
+        if self.components[source[0]] != self.components[target[0]] {
+            return Err(GeodesicError::Unreachable);
+        }
+        let field = self.distance_from(&[*from])?;
+
+        let support: Vec<usize> = source
+            .iter()
+            .zip(from.barycentric)
+            .filter(|(_, w)| *w > BARYCENTRIC_EPSILON)
+            .map(|(v, _)| *v)
+            .collect();
+        let is_source = |t: usize| support.iter().all(|v| self.triangles[t].contains(v));
+
+        let mut points = vec![self.point(to)];
+        let mut location = Location::Face(*to);
+        let max_steps = 4 * self.triangles.len() + 16;
+
+        for _ in 0..max_steps {
+            let (next, point) = match location {
+                Location::Face(at) if is_source(at.triangle) => break,
+                Location::Vertex(v) if self.vertex_triangles[v].iter().any(|&t| is_source(t)) => break,
+                Location::Face(at) => self.descend_face(&field, &at),
+                Location::Vertex(v) => self.descend_vertex(&field, v)?,
+            };
+            if let Some(point) = point {
+                points.push(point);
+            }
+            location = next;
+        }
+        if !matches!(location, Location::Face(at) if is_source(at.triangle))
+            && !matches!(location, Location::Vertex(v) if self.vertex_triangles[v].iter().any(|&t| is_source(t)))
+        {
+            return Err(GeodesicError::PathNotFound(format!(
+                "no route after {} steps",
+                max_steps
+            )));
+        }
+        points.push(self.point(from));
+
+        points.reverse();
+        points.dedup_by(|a, b| (*a - *b).norm() <= EPSILON);
+        let length = points.windows(2).map(|w| (w[1] - w[0]).norm()).sum();
+        Ok(GeodesicPath { points, length })
+    }
+
+    /// Shortest path between the surface points closest to `from` and `to`
+    pub fn shortest_path_between(&self, from: &Point3, to: &Point3) -> Result<GeodesicPath, GeodesicError> {
+        self.shortest_path(&self.closest_point(from), &self.closest_point(to))
+    }
+
+    fn triangle(&self, at: &SurfacePoint) -> Result<[usize; 3], GeodesicError> {
+        self.triangles
+            .get(at.triangle)
+            .copied()
+            .filter(|_| at.barycentric.iter().all(|w| w.is_finite()))
+            .ok_or(GeodesicError::InvalidPoint(at.triangle))
+    }
+
+    /// Gradient of a per-vertex function over a triangle
+    fn gradient(&self, tri: &[usize; 3], values: &[f64]) -> Vector3 {
+        let [a, b, c] = tri.map(|v| self.positions[v]);
+        let normal = triangle_normal(&a, &b, &c);
+        let twice_area = normal.norm();
+        let n = normal / twice_area;
+        let corners = [(a, b, c), (b, c, a), (c, a, b)];
+        let mut gradient = Vector3::zeros();
+        for (corner, (_, next, prev)) in corners.iter().enumerate() {
+            gradient += n.cross(&(prev - next)) * values[tri[corner]];
+        }
+        gradient / twice_area
+    }
+
+    /// Gradients of the barycentric coordinates over a triangle
+    fn barycentric_gradients(&self, tri: &[usize; 3]) -> [Vector3; 3] {
+        let [a, b, c] = tri.map(|v| self.positions[v]);
+        let normal = triangle_normal(&a, &b, &c);
+        let twice_area = normal.norm();
+        let n = normal / twice_area;
+        [
+            n.cross(&(c - b)) / twice_area,
+            n.cross(&(a - c)) / twice_area,
+            n.cross(&(b - a)) / twice_area,
+        ]
+    }
+
+    /// Walk straight downhill across a triangle to its far edge
+    fn descend_face(&self, field: &DistanceField, at: &SurfacePoint) -> (Location, Option<Point3>) {
+        let tri = self.triangles[at.triangle];
+        let lowest = |vertices: &[usize]| {
+            vertices
+                .iter()
+                .copied()
+                .min_by(|&a, &b| field.distances[a].total_cmp(&field.distances[b]))
+                .expect("triangle has corners")
+        };
+
+        let gradient = self.gradient(&tri, &field.distances);
+        if gradient.norm() <= EPSILON {
+            let v = lowest(&tri);
+            return (Location::Vertex(v), Some(self.positions[v]));
+        }
+        let direction = -gradient.normalize();
+        let rates = self.barycentric_gradients(&tri).map(|g| g.dot(&direction));
+
+        // Leave across the first edge whose opposite weight reaches zero
+        let exit = (0..3)
+            .filter(|&i| rates[i] < -BARYCENTRIC_EPSILON)
+            .map(|i| (i, (at.barycentric[i] / -rates[i]).max(0.0)))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((edge, step)) = exit else {
+            let v = lowest(&tri);
+            return (Location::Vertex(v), Some(self.positions[v]));
+        };
+
+        let mut barycentric = [0.0; 3];
+        for i in 0..3 {
+            barycentric[i] = (at.barycentric[i] + step * rates[i]).max(0.0);
+        }
+        barycentric[edge] = 0.0;
+        let total: f64 = barycentric.iter().sum();
+        barycentric.iter_mut().for_each(|w| *w /= total);
+        let exit_point = self.point(&SurfacePoint {
+            triangle: at.triangle,
+            barycentric,
+        });
+
+        if let Some(corner) = barycentric.iter().position(|&w| w > 1.0 - BARYCENTRIC_EPSILON) {
+            return (Location::Vertex(tri[corner]), Some(exit_point));
+        }
+        let (a, b) = (tri[(edge + 1) % 3], tri[(edge + 2) % 3]);
+        let neighbour = self.neighbours[at.triangle][edge];
+        match neighbour {
+            // Crossing into the next triangle, unless the flow turns straight
+            // back: then the valley runs along this edge
+            Some(next) if step > EPSILON => {
+                let corners = self.triangles[next];
+                let weight = |v: usize| tri.iter().position(|&c| c == v).map_or(0.0, |i| barycentric[i]);
+                let barycentric = corners.map(weight);
+                (
+                    Location::Face(SurfacePoint {
+                        triangle: next,
+                        barycentric,
+                    }),
+                    Some(exit_point),
+                )
+            }
+            _ => {
+                let v = lowest(&[a, b]);
+                (Location::Vertex(v), Some(self.positions[v]))
+            }
+        }
+    }
+
+    /// Leave a vertex into the triangle the flow points into, or along the
+    /// steepest edge
+    fn descend_vertex(&self, field: &DistanceField, v: usize) -> Result<(Location, Option<Point3>), GeodesicError> {
+        let mut best: Option<(f64, SurfacePoint)> = None;
+        for &t in &self.vertex_triangles[v] {
+            let tri = self.triangles[t];
+            let corner = tri
+                .iter()
+                .position(|&c| c == v)
+                .expect("vertex triangle contains vertex");
+            let gradient = self.gradient(&tri, &field.distances);
+            let slope = gradient.norm();
+            if slope <= EPSILON {
+                continue;
+            }
+            let rates = self.barycentric_gradients(&tri).map(|g| g.dot(&-gradient) / slope);
+            let inside = (0..3).all(|i| i == corner || rates[i] >= -BARYCENTRIC_EPSILON);
+            if inside && rates[corner] < -BARYCENTRIC_EPSILON && best.is_none_or(|(s, _)| slope > s) {
+                let mut barycentric = [0.0; 3];
+                barycentric[corner] = 1.0;
+                best = Some((
+                    slope,
+                    SurfacePoint {
+                        triangle: t,
+                        barycentric,
+                    },
+                ));
+            }
+        }
+        if let Some((_, at)) = best {
+            return Ok((Location::Face(at), None));
+        }
+
+        let here = field.distances[v];
+        let steepest = self.vertex_triangles[v]
+            .iter()
+            .flat_map(|&t| self.triangles[t])
+            .filter(|&u| u != v)
+            .map(|u| {
+                (
+                    u,
+                    (here - field.distances[u]) / (self.positions[u] - self.positions[v]).norm(),
+                )
+            })
+            .filter(|&(_, slope)| slope > 0.0)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match steepest {
+            Some((u, _)) => Ok((Location::Vertex(u), Some(self.positions[u]))),
+            None => Err(GeodesicError::PathNotFound(format!("stuck at vertex {}", v))),
+        }
+    }
+}
+
+/// Where a path trace currently is
+#[derive(Debug, Clone, Copy)]
+enum Location {
+    Face(SurfacePoint),
+    Vertex(usize),
+}
+
+fn triangle_normal(a: &Point3, b: &Point3, c: &Point3) -> Vector3 {
+    (b - a).cross(&(c - a))
+}
+
+/// Cotangent of the angle at `apex` between `a` and `b`
+fn cotangent(apex: &Point3, a: &Point3, b: &Point3) -> f64 {
+    let (u, v) = (a - apex, b - apex);
+    u.dot(&v) / u.cross(&v).norm().max(EPSILON * EPSILON)
+}
+
+/// Ordering that keeps a sparse matrix's nonzeros close to the diagonal
+fn reverse_cuthill_mckee(adjacency: &[Vec<usize>]) -> Vec<usize> {
+    let n = adjacency.len();
+    let mut by_degree: Vec<usize> = (0..n).collect();
+    by_degree.sort_by_key(|&v| adjacency[v].len());
+
+    let mut order = Vec::with_capacity(n);
+    let mut visited = vec![false; n];
+    for seed in by_degree {
+        if visited[seed] {
+            continue;
+        }
+        visited[seed] = true;
+        let mut head = order.len();
+        order.push(seed);
+        while head < order.len() {
+            let v = order[head];
+            head += 1;
+            let mut next: Vec<usize> = adjacency[v].iter().copied().filter(|&u| !visited[u]).collect();
+            next.sort_by_key(|&u| adjacency[u].len());
+            for u in next {
+                visited[u] = true;
+                order.push(u);
+            }
+        }
+    }
+    order.reverse();
+    order
+}
+
+/// Connected component of each vertex
+fn components(n: usize, triangles: &[[usize; 3]]) -> Vec<usize> {
+    fn find(parent: &mut [usize], mut v: usize) -> usize {
+        while parent[v] != v {
+            parent[v] = parent[parent[v]];
+            v = parent[v];
+        }
+        v
+    }
+
+    let mut parent: Vec<usize> = (0..n).collect();
+    for tri in triangles {
+        for corner in 1..3 {
+            let (a, b) = (find(&mut parent, tri[0]), find(&mut parent, tri[corner]));
+            parent[a] = b;
+        }
+    }
+    let mut used = vec![false; n];
+    triangles.iter().flatten().for_each(|&v| used[v] = true);
+    (0..n)
+        .map(|v| if used[v] { find(&mut parent, v) } else { usize::MAX })
+        .collect()
+}
+
+/// Barycentric weights of the point of triangle `abc` closest to `p`
+/// (Ericson, Real-Time Collision Detection 5.1.5)
+fn closest_on_triangle(p: &Point3, a: &Point3, b: &Point3, c: &Point3) -> [f64; 3] {
+    let (ab, ac, ap) = (b - a, c - a, p - a);
+    let (d1, d2) = (ab.dot(&ap), ac.dot(&ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return [1.0, 0.0, 0.0];
+    }
+    let bp = p - b;
+    let (d3, d4) = (ab.dot(&bp), ac.dot(&bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return [0.0, 1.0, 0.0];
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let v = d1 / (d1 - d3);
+        return [1.0 - v, v, 0.0];
+    }
+    let cp = p - c;
+    let (d5, d6) = (ab.dot(&cp), ac.dot(&cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return [0.0, 0.0, 1.0];
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let w = d2 / (d2 - d6);
+        return [1.0 - w, 0.0, w];
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return [0.0, 1.0 - w, w];
+    }
+    let denom = 1.0 / (va + vb + vc);
+    let (v, w) = (vb * denom, vc * denom);
+    [1.0 - v - w, v, w]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `n` x `n` grid of triangles over the unit square, scaled by `size`
+    fn grid(n: usize, size: f64) -> (Vec<Point3>, Vec<[usize; 3]>) {
+        let mut positions = Vec::new();
+        for j in 0..=n {
+            for i in 0..=n {
+                positions.push(Point3::new(i as f64 * size / n as f64, j as f64 * size / n as f64, 0.0));
+            }
+        }
+        let index = |i: usize, j: usize| j * (n + 1) + i;
+        let mut triangles = Vec::new();
+        for j in 0..n {
+            for i in 0..n {
+                triangles.push([index(i, j), index(i + 1, j), index(i + 1, j + 1)]);
+                triangles.push([index(i, j), index(i + 1, j + 1), index(i, j + 1)]);
+            }
+        }
+        (positions, triangles)
+    }
+
+    /// Surface of the cube [-1, 1]^3, each side split into `n` x `n` quads
+    fn cube(n: usize) -> (Vec<Point3>, Vec<[usize; 3]>) {
+        let mut positions = Vec::new();
+        let mut lookup: HashMap<[i64; 3], usize> = HashMap::new();
+        let mut vertex = |p: Vector3| {
+            let key = [p.x, p.y, p.z].map(|c| (c * 1e6).round() as i64);
+            *lookup.entry(key).or_insert_with(|| {
+                positions.push(Point3::from(p));
+                positions.len() - 1
+            })
+        };
+        let mut triangles = Vec::new();
+        for axis in 0..3 {
+            for sign in [-1.0, 1.0] {
+                let normal = Vector3::ith(axis, sign);
+                let u = Vector3::ith((axis + 1) % 3, 1.0);
+                let v = normal.cross(&u);
+                let at = |i: usize, j: usize| {
+                    normal + u * (2.0 * i as f64 / n as f64 - 1.0) + v * (2.0 * j as f64 / n as f64 - 1.0)
+                };
+                for j in 0..n {
+                    for i in 0..n {
+                        let quad = [at(i, j), at(i + 1, j), at(i + 1, j + 1), at(i, j + 1)].map(&mut vertex);
+                        triangles.push([quad[0], quad[1], quad[2]]);
+                        triangles.push([quad[0], quad[2], quad[3]]);
+                    }
+                }
+            }
+        }
+        (positions, triangles)
+    }
+
+    #[test]
+    fn test_flat_distances_match_straight_lines() {
+        let (positions, triangles) = grid(20, 2.0);
+        let solver = GeodesicSolver::from_triangles(positions.clone(), triangles, GeodesicSettings::default()).unwrap();
+        let field = solver.distance_from_vertices(&[VertexHandle(0)]).unwrap();
+
+        assert_eq!(field.vertex(VertexHandle(0)), 0.0);
+        for (i, p) in positions.iter().enumerate() {
+            let exact = p.coords.norm();
+            if exact > 0.5 {
+                assert!(
+                    (field.values()[i] - exact).abs() / exact < 0.05,
+                    "{} vs {}",
+                    field.values()[i],
+                    exact
+                );
+            }
+        }
+
+        let path = solver
+            .shortest_path_between(&Point3::new(0.15, 0.35, 0.0), &Point3::new(1.8, 1.45, 0.0))
+            .unwrap();
+        let exact = (Point3::new(1.8, 1.45, 0.0) - Point3::new(0.15, 0.35, 0.0)).norm();
+        assert!(
+            (path.length - exact).abs() / exact < 0.01,
+            "{} vs {}",
+            path.length,
+            exact
+        );
+        assert!((path.points[0] - Point3::new(0.15, 0.35, 0.0)).norm() < 1e-9);
+        assert!(path.points.iter().all(|p| p.z.abs() < EPSILON));
+    }
+
+    #[test]
+    fn test_path_wraps_around_cube() {
+        let (positions, triangles) = cube(8);
+        let solver = GeodesicSolver::from_triangles(positions, triangles, GeodesicSettings::default()).unwrap();
+
+        let top = Point3::new(0.1, 0.05, 1.0);
+        let bottom = Point3::new(0.1, 0.05, -1.0);
+        let path = solver.shortest_path_between(&top, &bottom).unwrap();
+
+        // Over the nearest side: 0.9 + 2 + 0.9
+        assert!((path.length - 3.8).abs() < 0.1, "{}", path.length);
+        for p in &path.points {
+            let outer = p.x.abs().max(p.y.abs()).max(p.z.abs());
+            assert!((outer - 1.0).abs() < 1e-9, "{:?} is off the surface", p);
+        }
+        assert!((path.points[path.points.len() - 1] - bottom).norm() < 1e-9);
+    }
+
+    #[test]
+    fn test_half_edge_mesh_components() {
+        // Two separate 8 x 8 quad patches
+        let mut mesh = HalfEdgeMesh::new();
+        let patch = |mesh: &mut HalfEdgeMesh, x: f64| {
+            let n = 8;
+            let v: Vec<VertexHandle> = (0..=n)
+                .flat_map(|j| (0..=n).map(move |i| (i, j)))
+                .map(|(i, j)| mesh.add_vertex(Point3::new(x + i as f64 / n as f64, j as f64 / n as f64, 0.0)))
+                .collect();
+            let at = |i: usize, j: usize| v[j * (n + 1) + i];
+            for j in 0..n {
+                for i in 0..n {
+                    mesh.add_face(&[at(i, j), at(i + 1, j), at(i + 1, j + 1), at(i, j + 1)])
+                        .unwrap();
+                }
+            }
+            [at(0, 0), at(n, 0), at(n, n)]
+        };
+        let near = patch(&mut mesh, 0.0);
+        let far = patch(&mut mesh, 5.0);
+
+        let solver = GeodesicSolver::new(&mesh).unwrap();
+        let field = solver.distance_from_vertices(&[near[0]]).unwrap();
+        assert!((field.vertex(near[1]) - 1.0).abs() < 0.1, "{}", field.vertex(near[1]));
+        assert!(
+            (field.vertex(near[2]) - 2f64.sqrt()).abs() < 0.1,
+            "{}",
+            field.vertex(near[2])
+        );
+        assert!(field.vertex(far[0]).is_infinite());
+
+        let from = solver.vertex_point(near[0]).unwrap();
+        let to = solver.vertex_point(far[0]).unwrap();
+        assert!(matches!(
+            solver.shortest_path(&from, &to),
+            Err(GeodesicError::Unreachable)
+        ));
+        assert!(matches!(solver.distance_from(&[]), Err(GeodesicError::NoSources)));
+    }
+}
@@ -0,0 +1,946 @@
+//! Surface-surface intersection
+//!
+//! Intersection curves between two surfaces, analytic (plane, cylinder,
+//! cone, sphere, torus) or NURBS, for section views, trimmed surfaces and
+//! STEP export. The intersector works on [`ParametricSurface`], so any
+//! surface implementing it can take part.
+//!
+//! The method is subdivide-and-march:
+//!
+//! 1. Both surfaces are sampled as a grid of patches, and every pair of
+//!    patches whose bounding boxes overlap gives a starting guess.
+//! 2. Each guess is refined by Gauss-Newton on (u1, v1, u2, v2) until the
+//!    two surface points coincide; these are the seeds.
+//! 3. From each seed not already on a curve, the curve is traced both ways
+//!    along the cross product of the surface normals, every step corrected
+//!    back onto both surfaces, until it closes on itself, leaves either
+//!    parameter domain (the exit point is solved onto the boundary) or the
+//!    surfaces become tangent.
+//!
+//! Curves keep their parameter-space images on both surfaces, which
+//! trimming and STEP pcurves need. Parameters on closed (periodic)
+//! directions are wrapped into the domain, so those images jump at the seam.
+
+use crate::core::{Point2, Point3, Vector3};
+use crate::geometry::surface::ParametricSurface;
+use nalgebra::{SMatrix, SVector, Unit, Vector4};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::{FRAC_PI_2, PI};
+
+/// Sine of the angle between normals below which surfaces count as tangent
+const TANGENT_SINE: f64 = 1e-6;
+
+/// Newton iterations per solve
+const MAX_ITERATIONS: usize = 30;
+
+/// Smallest step as a fraction of the nominal step
+const MIN_STEP_FRACTION: f64 = 1.0 / 64.0;
+
+/// Local coordinate frame of an analytic surface
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Frame {
+    /// Origin
+    pub origin: Point3,
+    /// Unit X axis
+    pub x_axis: Vector3,
+    /// Unit Y axis
+    pub y_axis: Vector3,
+    /// Unit Z axis; the surface's axis or normal
+    pub z_axis: Vector3,
+}
+
+impl Frame {
+    /// Frame at `origin` with Z along `axis` and X, Y chosen perpendicular
+    pub fn new(origin: Point3, axis: Vector3) -> Self {
+        let z_axis = axis.normalize();
+        let helper = if z_axis.x.abs() < 0.9 {
+            Vector3::x()
+        } else {
+            Vector3::y()
+        };
+        let x_axis = helper.cross(&z_axis).normalize();
+        let y_axis = z_axis.cross(&x_axis);
+        Self {
+            origin,
+            x_axis,
+            y_axis,
+            z_axis,
+        }
+    }
+
+    /// Unit vector at angle `u` in the XY plane
+    fn radial(&self, u: f64) -> Vector3 {
+        self.x_axis * u.cos() + self.y_axis * u.sin()
+    }
+
+    /// Derivative of [`Frame::radial`] with respect to `u`
+    fn tangential(&self, u: f64) -> Vector3 {
+        self.y_axis * u.cos() - self.x_axis * u.sin()
+    }
+}
+
+/// Analytic surface
+///
+/// Angles are in radians. Periodic directions run over [0, 2π).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AnalyticSurface {
+    /// Rectangle in the frame's XY plane; u along X, v along Y
+    Plane {
+        frame: Frame,
+        half_width: f64,
+        half_height: f64,
+    },
+    /// Cylinder around the frame's Z axis from the origin up to `height`;
+    /// u around the axis, v along it
+    Cylinder { frame: Frame, radius: f64, height: f64 },
+    /// Cone with its apex at the origin opening along Z up to `height`; u
+    /// around the axis, v along it
+    Cone { frame: Frame, half_angle: f64, height: f64 },
+    /// Sphere; u is longitude, v latitude in [-π/2, π/2]
+    Sphere { frame: Frame, radius: f64 },
+    /// Torus around the frame's Z axis; u around the axis, v around the tube
+    Torus {
+        frame: Frame,
+        major_radius: f64,
+        minor_radius: f64,
+    },
+}
+
+impl AnalyticSurface {
+    /// Square plane through `origin` with the given normal
+    pub fn plane(origin: Point3, normal: Vector3, half_size: f64) -> Self {
+        Self::Plane {
+            frame: Frame::new(origin, normal),
+            half_width: half_size,
+            half_height: half_size,
+        }
+    }
+
+    /// Cylinder standing on `base` along `axis`
+    pub fn cylinder(base: Point3, axis: Vector3, radius: f64, height: f64) -> Self {
+        Self::Cylinder {
+            frame: Frame::new(base, axis),
+            radius,
+            height,
+        }
+    }
+
+    /// Cone with its apex at `apex` opening along `axis`
+    pub fn cone(apex: Point3, axis: Vector3, half_angle: f64, height: f64) -> Self {
+        Self::Cone {
+            frame: Frame::new(apex, axis),
+            half_angle,
+            height,
+        }
+    }
+
+    /// Sphere around `center`
+    pub fn sphere(center: Point3, radius: f64) -> Self {
+        Self::Sphere {
+            frame: Frame::new(center, Vector3::z()),
+            radius,
+        }
+    }
+
+    /// Torus around `center` with the tube circling `axis`
+    pub fn torus(center: Point3, axis: Vector3, major_radius: f64, minor_radius: f64) -> Self {
+        Self::Torus {
+            frame: Frame::new(center, axis),
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl ParametricSurface for AnalyticSurface {
+    fn evaluate(&self, u: f64, v: f64) -> Point3 {
+        match self {
+            Self::Plane { frame, .. } => frame.origin + frame.x_axis * u + frame.y_axis * v,
+            Self::Cylinder { frame, radius, .. } => frame.origin + frame.radial(u) * *radius + frame.z_axis * v,
+            Self::Cone { frame, half_angle, .. } => {
+                frame.origin + (frame.radial(u) * half_angle.tan() + frame.z_axis) * v
+            }
+            Self::Sphere { frame, radius } => {
+                frame.origin + (frame.radial(u) * v.cos() + frame.z_axis * v.sin()) * *radius
+            }
+            Self::Torus {
+                frame,
+                major_radius,
+                minor_radius,
+            } => {
+                frame.origin
+                    + frame.radial(u) * (major_radius + minor_radius * v.cos())
+                    + frame.z_axis * (minor_radius * v.sin())
+            }
+        }
+    }
+
+    fn normal(&self, u: f64, v: f64) -> Unit<Vector3> {
+        match self {
+            // Exact normals stay defined at the apex and poles, where the
+            // partials vanish
+            Self::Plane { frame, .. } => Unit::new_normalize(frame.z_axis),
+            Self::Cylinder { frame, .. } => Unit::new_normalize(frame.radial(u)),
+            Self::Cone { frame, half_angle, .. } => {
+                Unit::new_normalize(frame.radial(u) * half_angle.cos() - frame.z_axis * half_angle.sin())
+            }
+            Self::Sphere { frame, .. } => Unit::new_normalize(frame.radial(u) * v.cos() + frame.z_axis * v.sin()),
+            Self::Torus { frame, .. } => Unit::new_normalize(frame.radial(u) * v.cos() + frame.z_axis * v.sin()),
+        }
+    }
+
+    fn parameter_range(&self) -> ((f64, f64), (f64, f64)) {
+        match self {
+            Self::Plane {
+                half_width,
+                half_height,
+                ..
+            } => ((-half_width, *half_width), (-half_height, *half_height)),
+            Self::Cylinder { height, .. } | Self::Cone { height, .. } => ((0.0, 2.0 * PI), (0.0, *height)),
+            Self::Sphere { .. } => ((0.0, 2.0 * PI), (-FRAC_PI_2, FRAC_PI_2)),
+            Self::Torus { .. } => ((0.0, 2.0 * PI), (0.0, 2.0 * PI)),
+        }
+    }
+
+    fn partial_u(&self, u: f64, v: f64) -> Vector3 {
+        match self {
+            Self::Plane { frame, .. } => frame.x_axis,
+            Self::Cylinder { frame, radius, .. } => frame.tangential(u) * *radius,
+            Self::Cone { frame, half_angle, .. } => frame.tangential(u) * (half_angle.tan() * v),
+            Self::Sphere { frame, radius } => frame.tangential(u) * (radius * v.cos()),
+            Self::Torus {
+                frame,
+                major_radius,
+                minor_radius,
+            } => frame.tangential(u) * (major_radius + minor_radius * v.cos()),
+        }
+    }
+
+    fn partial_v(&self, u: f64, v: f64) -> Vector3 {
+        match self {
+            Self::Plane { frame, .. } => frame.y_axis,
+            Self::Cylinder { frame, .. } => frame.z_axis,
+            Self::Cone { frame, half_angle, .. } => frame.radial(u) * half_angle.tan() + frame.z_axis,
+            Self::Sphere { frame, radius } => (frame.z_axis * v.cos() - frame.radial(u) * v.sin()) * *radius,
+            Self::Torus {
+                frame, minor_radius, ..
+            } => (frame.z_axis * v.cos() - frame.radial(u) * v.sin()) * *minor_radius,
+        }
+    }
+}
+
+/// Intersection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntersectionSettings {
+    /// Patches per parameter direction when looking for seeds; raise it to
+    /// find small closed loops
+    pub grid: usize,
+    /// Distance between curve points; `None` uses a hundredth of the
+    /// smaller surface's size
+    pub step: Option<f64>,
+    /// Distance at which two surface points coincide
+    pub tolerance: f64,
+    /// Largest turn of the curve tangent per step, in radians
+    pub max_turn: f64,
+    /// Most points on one curve
+    pub max_points: usize,
+}
+
+impl Default for IntersectionSettings {
+    fn default() -> Self {
+        Self {
+            grid: 24,
+            step: None,
+            tolerance: 1e-8,
+            max_turn: 0.1,
+            max_points: 100_000,
+        }
+    }
+}
+
+/// Intersection errors
+#[derive(Debug, thiserror::Error)]
+pub enum IntersectionError {
+    #[error("Surface has an empty or non-finite parameter domain")]
+    EmptyDomain,
+
+    #[error("Invalid intersection settings: {0}")]
+    InvalidSettings(String),
+}
+
+/// One branch of the intersection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntersectionCurve {
+    /// Points along the curve
+    pub points: Vec<Point3>,
+    /// Parameters of each point on the first surface
+    pub params_a: Vec<Point2>,
+    /// Parameters of each point on the second surface
+    pub params_b: Vec<Point2>,
+    /// Whether the last point joins back to the first
+    pub closed: bool,
+}
+
+impl IntersectionCurve {
+    /// Length of the polyline, including the closing segment
+    pub fn length(&self) -> f64 {
+        let open: f64 = self.points.windows(2).map(|w| (w[1] - w[0]).norm()).sum();
+        match (self.closed, self.points.first(), self.points.last()) {
+            (true, Some(first), Some(last)) => open + (first - last).norm(),
+            _ => open,
+        }
+    }
+}
+
+/// Parameter domain of one surface
+#[derive(Debug, Clone, Copy)]
+struct Domain {
+    min: [f64; 2],
+    max: [f64; 2],
+    closed: [bool; 2],
+    /// Per direction, whether its low and high edges collapse to a point
+    poles: [[bool; 2]; 2],
+}
+
+impl Domain {
+    fn of(surface: &dyn ParametricSurface, tolerance: f64) -> Result<Self, IntersectionError> {
+        let ((u0, u1), (v0, v1)) = surface.parameter_range();
+        if ![u0, u1, v0, v1].iter().all(|x| x.is_finite()) || u1 <= u0 || v1 <= v0 {
+            return Err(IntersectionError::EmptyDomain);
+        }
+
+        // A direction is closed when its two ends meet all along the other
+        let seam = |a: &dyn Fn(f64) -> Point3, b: &dyn Fn(f64) -> Point3, lo: f64, hi: f64| {
+            (0..=4).all(|i| {
+                let t = lo + (hi - lo) * i as f64 / 4.0;
+                (a(t) - b(t)).norm() <= tolerance.max(1e-9) * 10.0
+            })
+        };
+        let closed_u = seam(&|v| surface.evaluate(u0, v), &|v| surface.evaluate(u1, v), v0, v1);
+        let closed_v = seam(&|u| surface.evaluate(u, v0), &|u| surface.evaluate(u, v1), u0, u1);
+
+        // An edge is a pole when it collapses to the point at its start
+        let pole_u = |u: f64| seam(&|v| surface.evaluate(u, v), &|_| surface.evaluate(u, v0), v0, v1);
+        let pole_v = |v: f64| seam(&|u| surface.evaluate(u, v), &|_| surface.evaluate(u0, v), u0, u1);
+
+        Ok(Self {
+            min: [u0, v0],
+            max: [u1, v1],
+            closed: [closed_u, closed_v],
+            poles: [[pole_u(u0), pole_u(u1)], [pole_v(v0), pole_v(v1)]],
+        })
+    }
+
+    fn span(&self, k: usize) -> f64 {
+        self.max[k] - self.min[k]
+    }
+
+    /// Carry a parameter pair over poles and seams back into the domain
+    fn wrap(&self, uv: &mut [f64]) {
+        for k in 0..2 {
+            // Past a pole of a revolved surface is the meridian half a turn
+            // round, coming back down
+            let other = 1 - k;
+            if self.closed[k] || !self.closed[other] {
+                continue;
+            }
+            if self.poles[k][0] && uv[k] < self.min[k] {
+                uv[k] = 2.0 * self.min[k] - uv[k];
+                uv[other] += self.span(other) / 2.0;
+            } else if self.poles[k][1] && uv[k] > self.max[k] {
+                uv[k] = 2.0 * self.max[k] - uv[k];
+                uv[other] += self.span(other) / 2.0;
+            }
+        }
+        for (k, x) in uv.iter_mut().enumerate().take(2) {
+            if self.closed[k] {
+                *x = self.min[k] + (*x - self.min[k]).rem_euclid(self.span(k));
+            }
+        }
+    }
+
+    /// Clamp open directions to the domain
+    fn clamp(&self, uv: &mut [f64]) {
+        for (k, x) in uv.iter_mut().enumerate().take(2) {
+            if !self.closed[k] {
+                *x = x.clamp(self.min[k], self.max[k]);
+            }
+        }
+    }
+
+    /// Open direction furthest outside the domain, with the bound it crossed
+    /// and by how much relative to the span
+    fn violation(&self, uv: &[f64]) -> Option<(usize, f64, f64)> {
+        (0..2)
+            .filter(|&k| !self.closed[k])
+            .filter_map(|k| {
+                let slack = 1e-9 * self.span(k);
+                if uv[k] < self.min[k] - slack {
+                    Some((k, self.min[k], (self.min[k] - uv[k]) / self.span(k)))
+                } else if uv[k] > self.max[k] + slack {
+                    Some((k, self.max[k], (uv[k] - self.max[k]) / self.span(k)))
+                } else {
+                    None
+                }
+            })
+            .max_by(|a, b| a.2.total_cmp(&b.2))
+    }
+}
+
+/// One surface with its domain
+struct Side<'a> {
+    surface: &'a dyn ParametricSurface,
+    domain: Domain,
+}
+
+/// Point, partials and unit normal at a parameter pair
+struct Sample {
+    point: Point3,
+    du: Vector3,
+    dv: Vector3,
+    normal: Vector3,
+}
+
+impl Side<'_> {
+    /// Evaluate, extending the surface linearly past open edges so Newton
+    /// iterates may step outside the domain
+    fn sample(&self, uv: &[f64]) -> Sample {
+        let mut inside = [uv[0], uv[1]];
+        self.domain.clamp(&mut inside);
+        let (u, v) = (inside[0], inside[1]);
+        let du = self.surface.partial_u(u, v);
+        let dv = self.surface.partial_v(u, v);
+        let point = self.surface.evaluate(u, v) + du * (uv[0] - u) + dv * (uv[1] - v);
+        Sample {
+            point,
+            du,
+            dv,
+            normal: self.surface.normal(u, v).into_inner(),
+        }
+    }
+}
+
+/// Point on both surfaces: (u, v) on the first, then on the second
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    point: Point3,
+    params: Vector4<f64>,
+}
+
+/// How a march ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MarchEnd {
+    Closed,
+    Open,
+}
+
+/// Surface-surface intersector
+#[derive(Debug, Clone, Default)]
+pub struct SurfaceIntersector {
+    settings: IntersectionSettings,
+}
+
+impl SurfaceIntersector {
+    /// Intersector with default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Intersector with the given settings
+    pub fn with_settings(settings: IntersectionSettings) -> Self {
+        Self { settings }
+    }
+
+    /// Settings in use
+    pub fn settings(&self) -> &IntersectionSettings {
+        &self.settings
+    }
+
+    /// Intersection curves of two surfaces
+    ///
+    /// Returns no curves when the surfaces don't meet or only touch;
+    /// coincident regions are not reported.
+    pub fn intersect(
+        &self,
+        a: &dyn ParametricSurface,
+        b: &dyn ParametricSurface,
+    ) -> Result<Vec<IntersectionCurve>, IntersectionError> {
+        let settings = &self.settings;
+        if settings.grid == 0 || settings.max_points < 2 {
+            return Err(IntersectionError::InvalidSettings(
+                "grid and max_points must be positive".to_string(),
+            ));
+        }
+        if !(settings.tolerance > 0.0 && settings.max_turn > 0.0) {
+            return Err(IntersectionError::InvalidSettings(
+                "tolerance and max_turn must be positive".to_string(),
+            ));
+        }
+
+        let a = Side {
+            surface: a,
+            domain: Domain::of(a, settings.tolerance)?,
+        };
+        let b = Side {
+            surface: b,
+            domain: Domain::of(b, settings.tolerance)?,
+        };
+
+        let grid_a = PatchGrid::new(&a, settings.grid);
+        let grid_b = PatchGrid::new(&b, settings.grid);
+        let step = match settings.step {
+            Some(step) if step > 0.0 => step,
+            Some(_) => return Err(IntersectionError::InvalidSettings("step must be positive".to_string())),
+            None => grid_a.size().min(grid_b.size()) / 100.0,
+        };
+        if step <= 0.0 || !step.is_finite() {
+            return Err(IntersectionError::EmptyDomain);
+        }
+
+        let seeds = self.seeds(&a, &b, &grid_a, &grid_b, step);
+        let mut curves: Vec<IntersectionCurve> = Vec::new();
+        for seed in seeds {
+            let on_curve = curves
+                .iter()
+                .any(|curve| distance_to_polyline(&seed.point, &curve.points, curve.closed) < step * 0.1);
+            if on_curve {
+                continue;
+            }
+            if let Some(curve) = self.trace(&a, &b, seed, step) {
+                curves.push(curve);
+            }
+        }
+        Ok(curves)
+    }
+
+    /// Refine every overlapping patch pair to a point on both surfaces
+    fn seeds(&self, a: &Side, b: &Side, grid_a: &PatchGrid, grid_b: &PatchGrid, step: f64) -> Vec<Node> {
+        let mut seeds: Vec<Node> = Vec::new();
+        for pa in &grid_a.patches {
+            if !overlaps(&pa.bounds, &grid_b.bounds) {
+                continue;
+            }
+            for pb in &grid_b.patches {
+                if !overlaps(&pa.bounds, &pb.bounds) {
+                    continue;
+                }
+                let start = Vector4::new(pa.center[0], pa.center[1], pb.center[0], pb.center[1]);
+                let Some(node) = self.refine(a, b, start) else {
+                    continue;
+                };
+                if tangent(a, b, &node.params).is_none() {
+                    continue;
+                }
+                if seeds.iter().all(|s| (s.point - node.point).norm() > step * 0.5) {
+                    seeds.push(node);
+                }
+            }
+        }
+        seeds
+    }
+
+    /// Gauss-Newton from a guess to a point on both surfaces
+    fn refine(&self, a: &Side, b: &Side, mut x: Vector4<f64>) -> Option<Node> {
+        for _ in 0..MAX_ITERATIONS {
+            let (sa, sb) = (a.sample(&x.as_slice()[..2]), b.sample(&x.as_slice()[2..]));
+            let f = sa.point - sb.point;
+            if !f.iter().all(|c| c.is_finite()) {
+                return None;
+            }
+            if f.norm() <= self.settings.tolerance {
+                return inside(a, b, &x).then_some(Node {
+                    point: sa.point,
+                    params: x,
+                });
+            }
+            let j = SMatrix::<f64, 3, 4>::from_columns(&[sa.du, sa.dv, -sb.du, -sb.dv]);
+            x += limit(damped_step(&j, &f)?, a, b);
+            a.domain.wrap(&mut x.as_mut_slice()[..2]);
+            b.domain.wrap(&mut x.as_mut_slice()[2..]);
+            a.domain.clamp(&mut x.as_mut_slice()[..2]);
+            b.domain.clamp(&mut x.as_mut_slice()[2..]);
+        }
+        None
+    }
+
+    /// Newton onto both surfaces with one more condition: the projection
+    /// along `direction` lands on `target` (`None`), or parameter `k`
+    /// equals the bound (`Some((k, bound))`)
+    fn correct(
+        &self,
+        a: &Side,
+        b: &Side,
+        mut x: Vector4<f64>,
+        target: &Point3,
+        direction: &Vector3,
+        fixed: Option<(usize, f64)>,
+    ) -> Option<Node> {
+        for _ in 0..MAX_ITERATIONS {
+            let (sa, sb) = (a.sample(&x.as_slice()[..2]), b.sample(&x.as_slice()[2..]));
+            let gap = sa.point - sb.point;
+            let last = match fixed {
+                Some((k, bound)) => x[k] - bound,
+                None => direction.dot(&(sa.point - target)),
+            };
+            let f = SVector::<f64, 4>::new(gap.x, gap.y, gap.z, last);
+            if !f.iter().all(|c| c.is_finite()) {
+                return None;
+            }
+            if gap.norm() <= self.settings.tolerance && last.abs() <= self.settings.tolerance {
+                return Some(Node {
+                    point: sa.point,
+                    params: x,
+                });
+            }
+
+            let mut j = SMatrix::<f64, 4, 4>::zeros();
+            j.fixed_view_mut::<3, 4>(0, 0)
+                .copy_from(&SMatrix::<f64, 3, 4>::from_columns(&[sa.du, sa.dv, -sb.du, -sb.dv]));
+            match fixed {
+                Some((k, _)) => j[(3, k)] = 1.0,
+                None => {
+                    j[(3, 0)] = direction.dot(&sa.du);
+                    j[(3, 1)] = direction.dot(&sa.dv);
+                }
+            }
+            x += limit(damped_step(&j, &f)?, a, b);
+            a.domain.wrap(&mut x.as_mut_slice()[..2]);
+            b.domain.wrap(&mut x.as_mut_slice()[2..]);
+
+            // Iterates wandering a whole span outside have lost the curve
+            let far = |side: &Side, uv: &[f64]| {
+                (0..2).any(|k| {
+                    uv[k] < side.domain.min[k] - side.domain.span(k) || uv[k] > side.domain.max[k] + side.domain.span(k)
+                })
+            };
+            if far(a, &x.as_slice()[..2]) || far(b, &x.as_slice()[2..]) {
+                return None;
+            }
+        }
+        None
+    }
+
+    /// Trace the whole curve through a seed
+    fn trace(&self, a: &Side, b: &Side, seed: Node, step: f64) -> Option<IntersectionCurve> {
+        let (forward, end) = self.march(a, b, seed, step, 1.0);
+        let nodes = if end == MarchEnd::Closed {
+            forward
+        } else {
+            let (mut backward, _) = self.march(a, b, seed, step, -1.0);
+            backward.reverse();
+            backward.pop();
+            backward.extend(forward);
+            backward
+        };
+        if nodes.len() < 2 {
+            return None;
+        }
+
+        Some(IntersectionCurve {
+            points: nodes.iter().map(|n| n.point).collect(),
+            params_a: nodes.iter().map(|n| Point2::new(n.params[0], n.params[1])).collect(),
+            params_b: nodes.iter().map(|n| Point2::new(n.params[2], n.params[3])).collect(),
+            closed: end == MarchEnd::Closed,
+        })
+    }
+
+    /// Follow the curve from `start` in one direction
+    fn march(&self, a: &Side, b: &Side, start: Node, step: f64, sign: f64) -> (Vec<Node>, MarchEnd) {
+        let min_step = step * MIN_STEP_FRACTION;
+        let mut nodes = vec![start];
+        let Some(mut direction) = tangent(a, b, &start.params).map(|t| t * sign) else {
+            return (nodes, MarchEnd::Open);
+        };
+        let mut h = step;
+        let mut travelled = 0.0;
+
+        while nodes.len() < self.settings.max_points {
+            let current = *nodes.last().expect("march starts at the seed");
+
+            let to_start = start.point - current.point;
+            if travelled > 2.0 * step && to_start.norm() <= h && to_start.dot(&direction) > 0.0 {
+                return (nodes, MarchEnd::Closed);
+            }
+
+            let target = current.point + direction * h;
+            let Some(next) = self.correct(a, b, current.params, &target, &direction, None) else {
+                if h > min_step {
+                    h *= 0.5;
+                    continue;
+                }
+                break;
+            };
+
+            if !inside(a, b, &next.params) {
+                if let Some(exit) = self.exit(a, b, &current, &next, &direction) {
+                    if (exit.point - current.point).norm() > self.settings.tolerance * 10.0 {
+                        nodes.push(exit);
+                    }
+                }
+                break;
+            }
+
+            let advance = (next.point - current.point).dot(&direction);
+            let turn = tangent(a, b, &next.params).map(|t| if t.dot(&direction) < 0.0 { -t } else { t });
+            match turn {
+                Some(t) if advance > 0.0 && t.angle(&direction) <= self.settings.max_turn => {
+                    travelled += (next.point - current.point).norm();
+                    nodes.push(next);
+                    direction = t;
+                    h = (h * 1.5).min(step);
+                }
+                _ if h > min_step => h *= 0.5,
+                // Tangent surfaces or a cusp: stop here
+                _ => break,
+            }
+        }
+        (nodes, MarchEnd::Open)
+    }
+
+    /// Solve the point where the curve leaves a parameter domain between an
+    /// inside node and an outside one
+    fn exit(&self, a: &Side, b: &Side, inside_node: &Node, outside: &Node, direction: &Vector3) -> Option<Node> {
+        let mut crossed = outside.params;
+        // A corner exit crosses two bounds; fix the worst, then check again
+        for _ in 0..2 {
+            let violation = a
+                .domain
+                .violation(&crossed.as_slice()[..2])
+                .into_iter()
+                .chain(
+                    b.domain
+                        .violation(&crossed.as_slice()[2..])
+                        .map(|(k, bound, by)| (k + 2, bound, by)),
+                )
+                .max_by(|p, q| p.2.total_cmp(&q.2));
+            let Some((k, bound, _)) = violation else {
+                return Some(Node {
+                    point: a.sample(&crossed.as_slice()[..2]).point,
+                    params: crossed,
+                });
+            };
+            let node = self.correct(a, b, inside_node.params, &outside.point, direction, Some((k, bound)))?;
+            if inside(a, b, &node.params) {
+                return Some(node);
+            }
+            crossed = node.params;
+        }
+        None
+    }
+}
+
+/// Sampled bounding boxes of a surface
+struct PatchGrid {
+    bounds: [Point3; 2],
+    patches: Vec<Patch>,
+}
+
+struct Patch {
+    bounds: [Point3; 2],
+    center: [f64; 2],
+}
+
+impl PatchGrid {
+    fn new(side: &Side, n: usize) -> Self {
+        let param = |k: usize, i: usize| side.domain.min[k] + side.domain.span(k) * i as f64 / n as f64;
+        let points: Vec<Vec<Point3>> = (0..=n)
+            .map(|i| {
+                (0..=n)
+                    .map(|j| side.surface.evaluate(param(0, i), param(1, j)))
+                    .collect()
+            })
+            .collect();
+
+        let mut patches = Vec::with_capacity(n * n);
+        for i in 0..n {
+            for j in 0..n {
+                let center = [
+                    (param(0, i) + param(0, i + 1)) / 2.0,
+                    (param(1, j) + param(1, j + 1)) / 2.0,
+                ];
+                let corners = [
+                    points[i][j],
+                    points[i + 1][j],
+                    points[i][j + 1],
+                    points[i + 1][j + 1],
+                    side.surface.evaluate(center[0], center[1]),
+                ];
+                let mut bounds = bounds_of(&corners);
+                // Allow for the patch bulging between samples
+                let margin = (bounds[1] - bounds[0]).norm() * 0.1 + 1e-9;
+                bounds[0] -= Vector3::repeat(margin);
+                bounds[1] += Vector3::repeat(margin);
+                patches.push(Patch { bounds, center });
+            }
+        }
+
+        let all: Vec<Point3> = patches.iter().flat_map(|p| p.bounds).collect();
+        Self {
+            bounds: bounds_of(&all),
+            patches,
+        }
+    }
+
+    /// Diagonal of the sampled surface
+    fn size(&self) -> f64 {
+        (self.bounds[1] - self.bounds[0]).norm()
+    }
+}
+
+fn bounds_of(points: &[Point3]) -> [Point3; 2] {
+    let mut min = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+    let mut max = Point3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for p in points.iter().filter(|p| p.coords.iter().all(|c| c.is_finite())) {
+        min = min.inf(p);
+        max = max.sup(p);
+    }
+    [min, max]
+}
+
+fn overlaps(a: &[Point3; 2], b: &[Point3; 2]) -> bool {
+    (0..3).all(|k| a[0][k] <= b[1][k] && b[0][k] <= a[1][k])
+}
+
+fn inside(a: &Side, b: &Side, x: &Vector4<f64>) -> bool {
+    a.domain.violation(&x.as_slice()[..2]).is_none() && b.domain.violation(&x.as_slice()[2..]).is_none()
+}
+
+/// Unit tangent of the intersection, `None` where the surfaces are tangent
+fn tangent(a: &Side, b: &Side, x: &Vector4<f64>) -> Option<Vector3> {
+    let t = a
+        .sample(&x.as_slice()[..2])
+        .normal
+        .cross(&b.sample(&x.as_slice()[2..]).normal);
+    let sine = t.norm();
+    (sine > TANGENT_SINE).then(|| t / sine)
+}
+
+/// Damped least-squares Newton step: exact for a regular square system,
+/// minimum-norm for an underdetermined one, and still defined where a
+/// parameterization degenerates (poles, apexes)
+fn damped_step<const R: usize>(j: &SMatrix<f64, R, 4>, f: &SVector<f64, R>) -> Option<Vector4<f64>> {
+    let jt = j.transpose();
+    let mut normal = jt * j;
+    let damping = 1e-12 * (1.0 + normal.trace());
+    for i in 0..4 {
+        normal[(i, i)] += damping;
+    }
+    normal.cholesky().map(|c| -c.solve(&(jt * f)))
+}
+
+/// Keep a Newton step within half a domain per parameter
+fn limit(mut dx: Vector4<f64>, a: &Side, b: &Side) -> Vector4<f64> {
+    let spans = [a.domain.span(0), a.domain.span(1), b.domain.span(0), b.domain.span(1)];
+    let scale = (0..4)
+        .map(|k| (0.5 * spans[k] / dx[k].abs().max(f64::MIN_POSITIVE)).min(1.0))
+        .fold(1.0, f64::min);
+    dx *= scale;
+    dx
+}
+
+fn distance_to_polyline(p: &Point3, points: &[Point3], closed: bool) -> f64 {
+    let segment = |s: &Point3, e: &Point3| {
+        let d = e - s;
+        let len2 = d.norm_squared();
+        let t = if len2 > 0.0 {
+            ((p - s).dot(&d) / len2).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        (p - (s + d * t)).norm()
+    };
+    let closing = match (closed, points.first(), points.last()) {
+        (true, Some(first), Some(last)) => segment(last, first),
+        _ => f64::INFINITY,
+    };
+    points
+        .windows(2)
+        .map(|w| segment(&w[0], &w[1]))
+        .chain(points.first().map(|f| (p - f).norm()))
+        .fold(closing, f64::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine3d::nurbs::NurbsSurface;
+
+    fn assert_on(curve: &IntersectionCurve, f: impl Fn(&Point3) -> f64) {
+        for p in &curve.points {
+            assert!(f(p).abs() < 1e-6, "{:?} is off the surface by {}", p, f(p));
+        }
+    }
+
+    #[test]
+    fn test_plane_sphere_circle() {
+        let plane = AnalyticSurface::plane(Point3::new(0.0, 0.0, 0.5), Vector3::z(), 2.0);
+        let sphere = AnalyticSurface::sphere(Point3::origin(), 1.0);
+
+        let curves = SurfaceIntersector::new().intersect(&plane, &sphere).unwrap();
+        assert_eq!(curves.len(), 1);
+        let circle = &curves[0];
+        assert!(circle.closed);
+        assert_on(circle, |p| p.z - 0.5);
+        assert_on(circle, |p| p.coords.norm() - 1.0);
+
+        let radius = 0.75f64.sqrt();
+        assert!((circle.length() - 2.0 * PI * radius).abs() < 0.01);
+        for (p, uv) in circle.points.iter().zip(&circle.params_b) {
+            assert!((sphere.evaluate(uv.x, uv.y) - p).norm() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_plane_cylinder_lines_end_on_boundary() {
+        let cylinder = AnalyticSurface::cylinder(Point3::new(0.0, 0.0, -2.0), Vector3::z(), 1.0, 4.0);
+        let plane = AnalyticSurface::plane(Point3::origin(), Vector3::x(), 3.0);
+
+        let curves = SurfaceIntersector::new().intersect(&cylinder, &plane).unwrap();
+        assert_eq!(curves.len(), 2);
+        for line in &curves {
+            assert!(!line.closed);
+            assert_on(line, |p| p.x);
+            assert!((line.length() - 4.0).abs() < 1e-6);
+            let (first, last) = (line.points[0], *line.points.last().unwrap());
+            assert!((first.z.abs() - 2.0).abs() < 1e-6 && (last.z.abs() - 2.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_cylinder_cylinder_loops() {
+        // A thin cylinder piercing a wide one leaves a loop on each side
+        let wide = AnalyticSurface::cylinder(Point3::new(0.0, 0.0, -2.0), Vector3::z(), 1.0, 4.0);
+        let thin = AnalyticSurface::cylinder(Point3::new(-2.0, 0.0, 0.0), Vector3::x(), 0.5, 4.0);
+
+        let curves = SurfaceIntersector::new().intersect(&wide, &thin).unwrap();
+        assert_eq!(curves.len(), 2);
+        for curve in &curves {
+            assert!(curve.closed);
+            assert_on(curve, |p| p.x.hypot(p.y) - 1.0);
+            assert_on(curve, |p| p.y.hypot(p.z) - 0.5);
+        }
+        assert!(curves[0].points[0].x * curves[1].points[0].x < 0.0);
+    }
+
+    #[test]
+    fn test_nurbs_patch_with_sphere() {
+        let corners = vec![
+            vec![Point3::new(-2.0, -2.0, 0.25), Point3::new(-2.0, 2.0, 0.25)],
+            vec![Point3::new(2.0, -2.0, 0.25), Point3::new(2.0, 2.0, 0.25)],
+        ];
+        let patch = NurbsSurface::new(1, 1, corners, vec![vec![1.0; 2]; 2]).unwrap();
+        let sphere = AnalyticSurface::sphere(Point3::origin(), 1.0);
+
+        let curves = SurfaceIntersector::new().intersect(&patch, &sphere).unwrap();
+        assert_eq!(curves.len(), 1);
+        assert!(curves[0].closed);
+        assert_on(&curves[0], |p| p.z - 0.25);
+        assert_on(&curves[0], |p| p.coords.norm() - 1.0);
+        for (p, uv) in curves[0].points.iter().zip(&curves[0].params_a) {
+            assert!((patch.evaluate(uv.x, uv.y).unwrap() - p).norm() < 1e-6);
+        }
+
+        let far = AnalyticSurface::sphere(Point3::new(10.0, 0.0, 0.0), 1.0);
+        assert!(SurfaceIntersector::new().intersect(&patch, &far).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,651 @@
+//! Geometry kernel abstraction
+//!
+//! Solid modeling operations go through a [`GeometryKernel`]. The built-in
+//! kernel runs them on half-edge meshes with the operations of this module;
+//! an external B-rep kernel (an OpenCascade bridge, for example) can take
+//! over the operations it supports by implementing [`BRepBackend`] and
+//! registering an [`ExternalKernel`], which needs the `external-kernel`
+//! feature. Shapes cross the kernel boundary as native [`KernelShape`]s, so
+//! callers never handle the external kernel's own types.
+
+use super::blend::{BlendOperation, EdgeBlend};
+use super::boolean::{boolean_operation, BooleanError, BooleanOp};
+use super::brep::{Brep, BrepError};
+use super::mesh::{EdgeHandle, HalfEdgeMesh, MeshError};
+use super::nurbs::NurbsSurface;
+use super::tessellation::{AdaptiveTessellator, TessellationError, TessellationSettings};
+use super::topology::{ExtrudeOperation, RevolveOperation, ShellOperation, TopologyError};
+use crate::core::Point3;
+use crate::io::document::Vec3;
+use crate::io::stl::{StlMesh, StlTriangle};
+use crate::io::threemf::ThreeMfObject;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Name of the built-in kernel
+pub const BUILTIN_KERNEL: &str = "builtin";
+
+/// Modeling operations a kernel may provide
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum KernelOperation {
+    Boolean,
+    Extrude,
+    Revolve,
+    Shell,
+    Fillet,
+    Chamfer,
+    Tessellate,
+}
+
+impl fmt::Display for KernelOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            KernelOperation::Boolean => "boolean",
+            KernelOperation::Extrude => "extrude",
+            KernelOperation::Revolve => "revolve",
+            KernelOperation::Shell => "shell",
+            KernelOperation::Fillet => "fillet",
+            KernelOperation::Chamfer => "chamfer",
+            KernelOperation::Tessellate => "tessellate",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Native geometry passed to and returned by a kernel
+#[derive(Debug, Clone)]
+pub enum KernelShape {
+    /// Faceted solid or surface
+    Mesh(HalfEdgeMesh),
+    /// Exact surface patches
+    Surfaces(Vec<NurbsSurface>),
+    /// Boundary representation with exact face and edge geometry
+    Brep(Brep),
+}
+
+impl KernelShape {
+    /// Faceted form of the shape; surface patches are tessellated and
+    /// joined into one mesh, and B-reps meshed face by face
+    pub fn to_mesh(&self, settings: &TessellationSettings) -> KernelResult<HalfEdgeMesh> {
+        match self {
+            KernelShape::Mesh(mesh) => Ok(mesh.clone()),
+            KernelShape::Surfaces(surfaces) => {
+                let tessellator = AdaptiveTessellator::new(*settings);
+                let mut mesh = HalfEdgeMesh::new();
+                for surface in surfaces {
+                    append(&mut mesh, &tessellator.tessellate_surface(surface)?)?;
+                }
+                mesh.update_vertex_normals();
+                Ok(mesh)
+            }
+            KernelShape::Brep(brep) => Ok(brep.to_mesh(settings)?),
+        }
+    }
+
+    /// Facets of the shape for STL export
+    ///
+    /// Exports pass their own settings, usually the document's
+    /// [`tessellation`](crate::io::document::DocumentSettings::tessellation)
+    /// or a coarser set to keep files small; polygons are split into
+    /// triangle fans.
+    pub fn to_stl(&self, name: &str, settings: &TessellationSettings) -> KernelResult<StlMesh> {
+        let mesh = self.to_mesh(settings)?;
+        let corner = |p: Point3| Vec3::new(p.x, p.y, p.z);
+        let mut stl = StlMesh::new(name.to_string());
+        for fh in mesh.face_handles() {
+            let mut corners = Vec::new();
+            for vh in mesh.face_vertices(fh)? {
+                corners.push(corner(mesh.get_vertex(vh)?.position));
+            }
+            for k in 1..corners.len().saturating_sub(1) {
+                stl.triangles.push(StlTriangle::new(corners[0], corners[k], corners[k + 1]));
+            }
+        }
+        Ok(stl)
+    }
+
+    /// Object of the shape for 3MF export, the recommended 3D printing format
+    ///
+    /// Unlike [`to_stl`](Self::to_stl) the facets share their vertices;
+    /// the caller sets the model's unit and the object's material.
+    pub fn to_3mf(&self, name: &str, settings: &TessellationSettings) -> KernelResult<ThreeMfObject> {
+        let mesh = self.to_mesh(settings)?;
+        let mut object = ThreeMfObject::new(name);
+        let mut index = HashMap::new();
+        for vh in mesh.vertex_handles() {
+            let p = mesh.get_vertex(vh)?.position;
+            index.insert(vh, object.vertices.len());
+            object.vertices.push(Vec3::new(p.x, p.y, p.z));
+        }
+        for fh in mesh.face_handles() {
+            let corners: Vec<usize> = mesh.face_vertices(fh)?.iter().map(|vh| index[vh]).collect();
+            for k in 1..corners.len().saturating_sub(1) {
+                object.add_triangle([corners[0], corners[k], corners[k + 1]]);
+            }
+        }
+        Ok(object)
+    }
+}
+
+/// A solid modeling kernel
+///
+/// Every operation defaults to [`KernelError::Unsupported`], so a kernel
+/// only implements what it is good at and reports it through
+/// [`supports`](Self::supports). Edges are handles into the mesh form of
+/// the shape they belong to.
+pub trait GeometryKernel: Send + Sync {
+    /// Name the kernel is registered and selected by
+    fn name(&self) -> &str;
+
+    /// Whether the kernel implements an operation
+    fn supports(&self, operation: KernelOperation) -> bool;
+
+    /// Union, intersection or difference of two solids
+    fn boolean(&self, _a: &KernelShape, _b: &KernelShape, _op: BooleanOp) -> KernelResult<KernelShape> {
+        Err(unsupported(self.name(), KernelOperation::Boolean))
+    }
+
+    /// Sweep a closed profile along a direction
+    fn extrude(&self, _profile: &[Point3], _operation: &ExtrudeOperation) -> KernelResult<KernelShape> {
+        Err(unsupported(self.name(), KernelOperation::Extrude))
+    }
+
+    /// Sweep a profile around an axis
+    fn revolve(&self, _profile: &[Point3], _operation: &RevolveOperation) -> KernelResult<KernelShape> {
+        Err(unsupported(self.name(), KernelOperation::Revolve))
+    }
+
+    /// Offset the faces of a shape by a thickness
+    fn shell(&self, _shape: &KernelShape, _thickness: f64, _outward: bool) -> KernelResult<KernelShape> {
+        Err(unsupported(self.name(), KernelOperation::Shell))
+    }
+
+    /// Round edges with a constant radius
+    fn fillet(&self, _shape: &KernelShape, _edges: &[EdgeHandle], _radius: f64) -> KernelResult<KernelShape> {
+        Err(unsupported(self.name(), KernelOperation::Fillet))
+    }
+
+    /// Bevel edges with a constant distance
+    fn chamfer(&self, _shape: &KernelShape, _edges: &[EdgeHandle], _distance: f64) -> KernelResult<KernelShape> {
+        Err(unsupported(self.name(), KernelOperation::Chamfer))
+    }
+
+    /// Faceted form of a shape for display and export
+    fn tessellate(&self, _shape: &KernelShape, _settings: &TessellationSettings) -> KernelResult<HalfEdgeMesh> {
+        Err(unsupported(self.name(), KernelOperation::Tessellate))
+    }
+}
+
+/// The mesh kernel built from this module's operations
+///
+/// Surface patches are tessellated before any operation, so fillets and
+/// chamfers come out faceted; external kernels can round exact edges.
+#[derive(Debug, Clone, Default)]
+pub struct BuiltinKernel {
+    /// Tessellation applied to surface inputs
+    pub settings: TessellationSettings,
+}
+
+impl BuiltinKernel {
+    /// Create a kernel tessellating surface inputs with the given settings
+    pub fn new(settings: TessellationSettings) -> Self {
+        Self { settings }
+    }
+}
+
+impl GeometryKernel for BuiltinKernel {
+    fn name(&self) -> &str {
+        BUILTIN_KERNEL
+    }
+
+    fn supports(&self, _operation: KernelOperation) -> bool {
+        true
+    }
+
+    fn boolean(&self, a: &KernelShape, b: &KernelShape, op: BooleanOp) -> KernelResult<KernelShape> {
+        let a = a.to_mesh(&self.settings)?;
+        let b = b.to_mesh(&self.settings)?;
+        Ok(KernelShape::Mesh(boolean_operation(&a, &b, op)?))
+    }
+
+    fn extrude(&self, profile: &[Point3], operation: &ExtrudeOperation) -> KernelResult<KernelShape> {
+        Ok(KernelShape::Mesh(operation.extrude_profile(profile)?))
+    }
+
+    fn revolve(&self, profile: &[Point3], operation: &RevolveOperation) -> KernelResult<KernelShape> {
+        Ok(KernelShape::Mesh(operation.revolve_profile(profile)?))
+    }
+
+    fn shell(&self, shape: &KernelShape, thickness: f64, outward: bool) -> KernelResult<KernelShape> {
+        let mesh = shape.to_mesh(&self.settings)?;
+        let shell = ShellOperation { thickness, outward, ..Default::default() };
+        Ok(KernelShape::Mesh(shell.shell_mesh(&mesh)?))
+    }
+
+    fn fillet(&self, shape: &KernelShape, edges: &[EdgeHandle], radius: f64) -> KernelResult<KernelShape> {
+        let mesh = shape.to_mesh(&self.settings)?;
+        let blend = BlendOperation::new().with_edges(edges.iter().map(|&e| (e, EdgeBlend::fillet(radius))));
+        Ok(KernelShape::Mesh(blend.apply(&mesh)?))
+    }
+
+    fn chamfer(&self, shape: &KernelShape, edges: &[EdgeHandle], distance: f64) -> KernelResult<KernelShape> {
+        let mesh = shape.to_mesh(&self.settings)?;
+        let blend = BlendOperation::new().with_edges(edges.iter().map(|&e| (e, EdgeBlend::chamfer(distance))));
+        Ok(KernelShape::Mesh(blend.apply(&mesh)?))
+    }
+
+    fn tessellate(&self, shape: &KernelShape, settings: &TessellationSettings) -> KernelResult<HalfEdgeMesh> {
+        shape.to_mesh(settings)
+    }
+}
+
+/// An external B-rep kernel
+///
+/// Implemented by bridges to third-party kernels. The backend converts
+/// native shapes into its own representation on [`import`](Self::import)
+/// and back on [`export`](Self::export); [`ExternalKernel`] does the
+/// round trip around every operation.
+#[cfg(feature = "external-kernel")]
+pub trait BRepBackend: Send + Sync {
+    /// The kernel's own shape type
+    type Shape;
+
+    /// Name the kernel is registered and selected by
+    fn name(&self) -> &str;
+
+    /// Whether the kernel implements an operation
+    fn supports(&self, operation: KernelOperation) -> bool;
+
+    /// Convert native geometry into a kernel shape
+    fn import(&self, shape: &KernelShape) -> KernelResult<Self::Shape>;
+
+    /// Convert a kernel shape into native geometry, faceting anything
+    /// without a native equivalent with the given settings
+    fn export(&self, shape: &Self::Shape, settings: &TessellationSettings) -> KernelResult<KernelShape>;
+
+    /// Union, intersection or difference of two solids
+    fn boolean(&self, _a: &Self::Shape, _b: &Self::Shape, _op: BooleanOp) -> KernelResult<Self::Shape> {
+        Err(unsupported(self.name(), KernelOperation::Boolean))
+    }
+
+    /// Sweep a closed profile along a direction
+    fn extrude(&self, _profile: &[Point3], _operation: &ExtrudeOperation) -> KernelResult<Self::Shape> {
+        Err(unsupported(self.name(), KernelOperation::Extrude))
+    }
+
+    /// Sweep a profile around an axis
+    fn revolve(&self, _profile: &[Point3], _operation: &RevolveOperation) -> KernelResult<Self::Shape> {
+        Err(unsupported(self.name(), KernelOperation::Revolve))
+    }
+
+    /// Offset the faces of a shape by a thickness
+    fn shell(&self, _shape: &Self::Shape, _thickness: f64, _outward: bool) -> KernelResult<Self::Shape> {
+        Err(unsupported(self.name(), KernelOperation::Shell))
+    }
+
+    /// Round edges with a constant radius
+    fn fillet(&self, _shape: &Self::Shape, _edges: &[EdgeHandle], _radius: f64) -> KernelResult<Self::Shape> {
+        Err(unsupported(self.name(), KernelOperation::Fillet))
+    }
+
+    /// Bevel edges with a constant distance
+    fn chamfer(&self, _shape: &Self::Shape, _edges: &[EdgeHandle], _distance: f64) -> KernelResult<Self::Shape> {
+        Err(unsupported(self.name(), KernelOperation::Chamfer))
+    }
+}
+
+/// Adapts a [`BRepBackend`] to the [`GeometryKernel`] interface
+#[cfg(feature = "external-kernel")]
+pub struct ExternalKernel<B: BRepBackend> {
+    backend: B,
+    settings: TessellationSettings,
+}
+
+#[cfg(feature = "external-kernel")]
+impl<B: BRepBackend> ExternalKernel<B> {
+    /// Wrap a backend, exporting results with default tessellation
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend,
+            settings: TessellationSettings::default(),
+        }
+    }
+
+    /// Tessellation used when exporting results
+    pub fn with_settings(mut self, settings: TessellationSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// The wrapped backend
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    fn export(&self, shape: &B::Shape) -> KernelResult<KernelShape> {
+        self.backend.export(shape, &self.settings)
+    }
+}
+
+#[cfg(feature = "external-kernel")]
+impl<B: BRepBackend> GeometryKernel for ExternalKernel<B> {
+    fn name(&self) -> &str {
+        self.backend.name()
+    }
+
+    fn supports(&self, operation: KernelOperation) -> bool {
+        // Any imported shape can be exported faceted
+        operation == KernelOperation::Tessellate || self.backend.supports(operation)
+    }
+
+    fn boolean(&self, a: &KernelShape, b: &KernelShape, op: BooleanOp) -> KernelResult<KernelShape> {
+        let a = self.backend.import(a)?;
+        let b = self.backend.import(b)?;
+        self.export(&self.backend.boolean(&a, &b, op)?)
+    }
+
+    fn extrude(&self, profile: &[Point3], operation: &ExtrudeOperation) -> KernelResult<KernelShape> {
+        self.export(&self.backend.extrude(profile, operation)?)
+    }
+
+    fn revolve(&self, profile: &[Point3], operation: &RevolveOperation) -> KernelResult<KernelShape> {
+        self.export(&self.backend.revolve(profile, operation)?)
+    }
+
+    fn shell(&self, shape: &KernelShape, thickness: f64, outward: bool) -> KernelResult<KernelShape> {
+        let shape = self.backend.import(shape)?;
+        self.export(&self.backend.shell(&shape, thickness, outward)?)
+    }
+
+    fn fillet(&self, shape: &KernelShape, edges: &[EdgeHandle], radius: f64) -> KernelResult<KernelShape> {
+        let shape = self.backend.import(shape)?;
+        self.export(&self.backend.fillet(&shape, edges, radius)?)
+    }
+
+    fn chamfer(&self, shape: &KernelShape, edges: &[EdgeHandle], distance: f64) -> KernelResult<KernelShape> {
+        let shape = self.backend.import(shape)?;
+        self.export(&self.backend.chamfer(&shape, edges, distance)?)
+    }
+
+    fn tessellate(&self, shape: &KernelShape, settings: &TessellationSettings) -> KernelResult<HalfEdgeMesh> {
+        let shape = self.backend.import(shape)?;
+        self.backend.export(&shape, settings)?.to_mesh(settings)
+    }
+}
+
+/// Registered kernels and the one preferred for modeling
+///
+/// The built-in kernel is always registered and preferred until another
+/// kernel is chosen. Operations the preferred kernel does not support fall
+/// back to the built-in kernel.
+pub struct KernelRegistry {
+    kernels: Vec<Arc<dyn GeometryKernel>>,
+    preferred: usize,
+}
+
+impl KernelRegistry {
+    /// Create a registry holding only the built-in kernel
+    pub fn new() -> Self {
+        Self {
+            kernels: vec![Arc::new(BuiltinKernel::default())],
+            preferred: 0,
+        }
+    }
+
+    /// Add a kernel, replacing any registered under the same name
+    pub fn register(&mut self, kernel: Arc<dyn GeometryKernel>) {
+        match self.kernels.iter().position(|k| k.name() == kernel.name()) {
+            Some(index) => self.kernels[index] = kernel,
+            None => self.kernels.push(kernel),
+        }
+    }
+
+    /// Prefer a registered kernel for modeling
+    pub fn prefer(&mut self, name: &str) -> KernelResult<()> {
+        self.preferred = self
+            .kernels
+            .iter()
+            .position(|k| k.name() == name)
+            .ok_or_else(|| KernelError::UnknownKernel(name.to_string()))?;
+        Ok(())
+    }
+
+    /// Go back to the built-in kernel
+    pub fn prefer_builtin(&mut self) {
+        self.preferred = 0;
+    }
+
+    /// The preferred kernel
+    pub fn preferred(&self) -> Arc<dyn GeometryKernel> {
+        self.kernels[self.preferred].clone()
+    }
+
+    /// A registered kernel by name
+    pub fn get(&self, name: &str) -> Option<Arc<dyn GeometryKernel>> {
+        self.kernels.iter().find(|k| k.name() == name).cloned()
+    }
+
+    /// Names of the registered kernels, built-in first
+    pub fn names(&self) -> Vec<&str> {
+        self.kernels.iter().map(|k| k.name()).collect()
+    }
+
+    /// The kernel to run an operation on: the preferred kernel when it
+    /// supports the operation, the built-in kernel otherwise
+    pub fn kernel_for(&self, operation: KernelOperation) -> Arc<dyn GeometryKernel> {
+        let preferred = &self.kernels[self.preferred];
+        if preferred.supports(operation) {
+            preferred.clone()
+        } else {
+            self.kernels[0].clone()
+        }
+    }
+}
+
+impl Default for KernelRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Copy the faces of `source` into `target`
+fn append(target: &mut HalfEdgeMesh, source: &HalfEdgeMesh) -> KernelResult<()> {
+    let mut vertices = HashMap::new();
+    for vh in source.vertex_handles() {
+        let position = source.get_vertex(vh)?.position;
+        vertices.insert(vh, target.add_vertex(position));
+    }
+    for fh in source.face_handles() {
+        let face: Vec<_> = source.face_vertices(fh)?.iter().map(|vh| vertices[vh]).collect();
+        target.add_face(&face)?;
+    }
+    Ok(())
+}
+
+fn unsupported(kernel: &str, operation: KernelOperation) -> KernelError {
+    KernelError::Unsupported {
+        kernel: kernel.to_string(),
+        operation,
+    }
+}
+
+/// Geometry kernel errors
+#[derive(Debug, thiserror::Error)]
+pub enum KernelError {
+    #[error("Kernel '{kernel}' does not support {operation}")]
+    Unsupported { kernel: String, operation: KernelOperation },
+
+    #[error("Unknown geometry kernel: {0}")]
+    UnknownKernel(String),
+
+    #[error("Shape conversion failed: {0}")]
+    Conversion(String),
+
+    #[error("Kernel operation failed: {0}")]
+    Operation(String),
+
+    #[error(transparent)]
+    Mesh(#[from] MeshError),
+
+    #[error(transparent)]
+    Boolean(#[from] BooleanError),
+
+    #[error(transparent)]
+    Topology(#[from] TopologyError),
+
+    #[error(transparent)]
+    Tessellation(#[from] TessellationError),
+
+    #[error(transparent)]
+    Brep(#[from] BrepError),
+}
+
+/// Result type for kernel operations
+pub type KernelResult<T> = Result<T, KernelError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points() -> KernelShape {
+        let mut mesh = HalfEdgeMesh::new();
+        for (x, y) in [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)] {
+            mesh.add_vertex(Point3::new(x, y, 0.0));
+        }
+        KernelShape::Mesh(mesh)
+    }
+
+    /// Kernel that only rounds edges, leaving the shape as it is
+    struct FilletOnly;
+
+    impl GeometryKernel for FilletOnly {
+        fn name(&self) -> &str {
+            "fillet-only"
+        }
+
+        fn supports(&self, operation: KernelOperation) -> bool {
+            operation == KernelOperation::Fillet
+        }
+
+        fn fillet(&self, shape: &KernelShape, _edges: &[EdgeHandle], _radius: f64) -> KernelResult<KernelShape> {
+            Ok(shape.clone())
+        }
+    }
+
+    #[test]
+    fn test_builtin_kernel() {
+        let registry = KernelRegistry::new();
+        assert_eq!(registry.names(), vec![BUILTIN_KERNEL]);
+
+        let kernel = registry.kernel_for(KernelOperation::Boolean);
+        assert_eq!(kernel.name(), BUILTIN_KERNEL);
+        let line = [Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0)];
+        assert!(matches!(
+            kernel.extrude(&line, &ExtrudeOperation::default()),
+            Err(KernelError::Topology(TopologyError::InsufficientVertices))
+        ));
+        let mesh = kernel
+            .tessellate(&KernelShape::Surfaces(Vec::new()), &TessellationSettings::default())
+            .unwrap();
+        assert_eq!(mesh.stats().faces, 0);
+
+        assert!(kernel.supports(KernelOperation::Fillet));
+        assert!(matches!(
+            kernel.fillet(&points(), &[EdgeHandle(0)], 0.1),
+            Err(KernelError::Topology(TopologyError::UnknownEdge(0)))
+        ));
+    }
+
+    #[test]
+    fn test_printing_exports_share_facets() {
+        let square = [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)].map(|(x, y)| Point3::new(x, y, 0.0));
+        let extrude = ExtrudeOperation {
+            direction: crate::core::Vector3::new(0.0, 0.0, 2.0),
+            capped: true,
+            ..Default::default()
+        };
+        let cube = KernelShape::Mesh(extrude.extrude_profile(&square).unwrap());
+        let settings = TessellationSettings::default();
+
+        let stl = cube.to_stl("cube", &settings).unwrap();
+        let object = cube.to_3mf("cube", &settings).unwrap();
+        assert_eq!(object.triangles.len(), stl.triangles.len());
+        assert_eq!(object.vertices.len(), 8);
+    }
+
+    #[test]
+    fn test_preferred_kernel_falls_back_to_builtin() {
+        let mut registry = KernelRegistry::default();
+        registry.register(Arc::new(FilletOnly));
+        assert!(matches!(registry.prefer("occt"), Err(KernelError::UnknownKernel(_))));
+        assert_eq!(registry.preferred().name(), BUILTIN_KERNEL);
+
+        registry.prefer("fillet-only").unwrap();
+        assert_eq!(registry.kernel_for(KernelOperation::Fillet).name(), "fillet-only");
+        assert_eq!(registry.kernel_for(KernelOperation::Extrude).name(), BUILTIN_KERNEL);
+
+        registry.prefer_builtin();
+        assert_eq!(registry.kernel_for(KernelOperation::Fillet).name(), BUILTIN_KERNEL);
+        assert!(registry.get("fillet-only").is_some());
+    }
+
+    #[cfg(feature = "external-kernel")]
+    #[test]
+    fn test_external_kernel_round_trip() {
+        /// Backend whose shapes are bare vertex lists
+        struct PointKernel;
+
+        impl BRepBackend for PointKernel {
+            type Shape = Vec<Point3>;
+
+            fn name(&self) -> &str {
+                "points"
+            }
+
+            fn supports(&self, operation: KernelOperation) -> bool {
+                operation == KernelOperation::Chamfer
+            }
+
+            fn import(&self, shape: &KernelShape) -> KernelResult<Self::Shape> {
+                match shape {
+                    KernelShape::Mesh(mesh) => Ok(mesh.vertices.iter().flatten().map(|v| v.position).collect()),
+                    KernelShape::Surfaces(_) => Err(KernelError::Conversion("surfaces".into())),
+                    KernelShape::Brep(_) => Err(KernelError::Conversion("b-rep".into())),
+                }
+            }
+
+            fn export(&self, shape: &Self::Shape, _settings: &TessellationSettings) -> KernelResult<KernelShape> {
+                let mut mesh = HalfEdgeMesh::new();
+                for point in shape {
+                    mesh.add_vertex(*point);
+                }
+                Ok(KernelShape::Mesh(mesh))
+            }
+
+            fn chamfer(&self, shape: &Self::Shape, _edges: &[EdgeHandle], _distance: f64) -> KernelResult<Self::Shape> {
+                Ok(shape[..3].to_vec())
+            }
+        }
+
+        let kernel = ExternalKernel::new(PointKernel);
+        assert!(kernel.supports(KernelOperation::Tessellate));
+        assert!(!kernel.supports(KernelOperation::Fillet));
+
+        let shape = points();
+        let KernelShape::Mesh(chamfered) = kernel.chamfer(&shape, &[], 0.5).unwrap() else {
+            panic!("expected a mesh");
+        };
+        assert_eq!(chamfered.stats().vertices, 3);
+        assert!(matches!(
+            kernel.fillet(&shape, &[], 0.5),
+            Err(KernelError::Unsupported { .. })
+        ));
+        assert!(matches!(
+            kernel.tessellate(&KernelShape::Surfaces(Vec::new()), &TessellationSettings::default()),
+            Err(KernelError::Conversion(_))
+        ));
+    }
+}
@@ -211,18 +211,21 @@ impl HalfEdgeMesh {
             self.edges[edge_handles[i].0] = Some(edge);
         }
 
-        // Compute face normal
-        let normal = self.compute_face_normal(face_handle)?;
-
         // Create face
         let face = Face {
             halfedge: halfedge_handles[0],
-            normal,
+            normal: Vector3::new(0.0, 0.0, 1.0),
             material_id: None,
             attributes: HashMap::new(),
         };
         self.faces[face_idx] = Some(face);
 
+        // Compute face normal
+        let normal = self.compute_face_normal(face_handle)?;
+        if let Some(ref mut face) = self.faces[face_idx] {
+            face.normal = normal;
+        }
+
         // Try to find and connect twin half-edges
         self.update_twins(face_handle)?;
 
@@ -318,9 +321,9 @@ impl HalfEdgeMesh {
 
         // Find twins
         for he_idx in 0..self.halfedges.len() {
-            if let Some(ref halfedge) = self.halfedges[he_idx] {
+            if let Some(halfedge) = self.halfedges[he_idx].clone() {
                 if halfedge.twin.is_none() {
-                    if let Some(ref prev_he) = self.halfedges[halfedge.prev.0] {
+                    if let Some(prev_he) = self.halfedges[halfedge.prev.0].clone() {
                         let v_from = prev_he.vertex.0;
                         let v_to = halfedge.vertex.0;
 
@@ -6,14 +6,34 @@
 //! ## Modules
 //!
 //! - `mesh`: Half-edge mesh data structure for robust topology
+//! - `brep`: Boundary representation solids (shells, faces, loops, edges,
+//!   vertices) with exact geometry, built and edited through Euler operators
+//! - `direct`: History-free push/pull of planar faces that carries coplanar and
+//!   tangent faces along and slides neighboring geometry
 //! - `boolean`: CSG boolean operations (union, intersection, difference)
+//!   with shared-face handling and solid validity checks
 //! - `nurbs`: NURBS curves and surfaces with evaluation
 //! - `tessellation`: Adaptive tessellation algorithms
-//! - `topology`: Extrude, revolve, sweep, loft operations
+//! - `topology`: Extrude (with draft), revolve, sweep (with twist) and guided loft
+//!   of closed profiles into solids, and shelling solids to a wall thickness
+//! - `blend`: Constant- and variable-radius edge fillets and distance/angle
+//!   chamfers, with tangent edge-chain selection and corner blending
+//! - `features`: Parametric feature history (sketch, extrude, fillet, shell, pattern)
+//!   with editing, reordering, suppression, rollback and dependent regeneration
 //! - `healing`: Mesh repair and healing algorithms
 //! - `simplification`: LOD generation and mesh decimation
 //! - `analysis`: Geometry analysis and mass properties
+//! - `draft`: Draft angle and undercut analysis of molded parts against a
+//!   pull direction
+//! - `sheetmetal`: Sheet metal parts with K-factor bend tables, flanges,
+//!   folded solids and flat patterns exported to DXF for laser cutting
+//! - `geodesic`: Geodesic distance (heat method) and shortest paths along
+//!   the surface, for routing cables and pipes
+//! - `intersection`: Surface-surface intersection curves between analytic
+//!   and NURBS surfaces
 //! - `constraints`: Geometric constraint solver
+//! - `kernel`: Pluggable modeling kernels; the built-in mesh kernel is the
+//!   default and external B-rep kernels plug in behind `external-kernel`
 //!
 //! ## Example
 //!
@@ -44,14 +64,23 @@
 //! ```
 
 pub mod mesh;
+pub mod brep;
+pub mod direct;
 pub mod boolean;
 pub mod nurbs;
 pub mod tessellation;
 pub mod topology;
+pub mod blend;
+pub mod features;
 pub mod healing;
 pub mod simplification;
 pub mod analysis;
+pub mod draft;
+pub mod sheetmetal;
+pub mod geodesic;
+pub mod intersection;
 pub mod constraints;
+pub mod kernel;
 
 // Re-export commonly used types
 pub use mesh::{
@@ -59,8 +88,15 @@ pub use mesh::{
     Vertex, Edge, Face, HalfEdge, MeshError, MeshStats,
 };
 
+pub use brep::{
+    Brep, BrepError, BrepResult, EdgeCurve, FaceSurface,
+};
+
+pub use direct::push_pull;
+
 pub use boolean::{
     BooleanOp, Plane, BSPTree, boolean_operation,
+    BooleanError, BooleanOptions, BooleanResult, MeshValidity, boolean_operation_with,
 };
 
 pub use nurbs::{
@@ -74,7 +110,18 @@ pub use tessellation::{
 
 pub use topology::{
     ExtrudeOperation, RevolveOperation, SweepOperation,
-    LoftOperation, ShellOperation, TopologyError,
+    LoftOperation, LoftGuide, ProfileRegion, ShellOperation, ShellFailure, ShellFailureReason,
+    TopologyError, faces_facing,
+};
+
+pub use blend::{
+    BlendOperation, EdgeBlend, edge_chain, variable_fillet_chain,
+    DEFAULT_CHAIN_TURN, DEFAULT_FILLET_SEGMENTS,
+};
+
+pub use features::{
+    Feature, FeatureError, FeatureId, FeatureKind, FeatureResult, FeatureStatus, FeatureTree,
+    PatternKind, RegenerationReport,
 };
 
 pub use healing::{
@@ -89,6 +136,31 @@ pub use analysis::{
     GeometryAnalyzer, MassProperties, AnalysisError,
 };
 
+pub use draft::{
+    DraftAnalysis, DraftClass, DraftSettings, FaceDraft,
+};
+
+pub use sheetmetal::{
+    Bend, BendDirection, BendLine, Flange, FlangeEdge, FlatPattern, KFactorTable, SheetMetalError,
+    SheetMetalPart, SheetMetalResult, SheetMetalRules, bend_allowance, bend_deduction,
+};
+
+pub use geodesic::{
+    DistanceField, GeodesicError, GeodesicPath, GeodesicSettings, GeodesicSolver, SurfacePoint,
+};
+
+pub use intersection::{
+    AnalyticSurface, Frame, IntersectionCurve, IntersectionError, IntersectionSettings, SurfaceIntersector,
+};
+
 pub use constraints::{
     Constraint, ConstraintType, ConstraintSolver, SolveResult,
 };
+
+pub use kernel::{
+    BuiltinKernel, GeometryKernel, KernelError, KernelOperation, KernelRegistry,
+    KernelResult, KernelShape, BUILTIN_KERNEL,
+};
+
+#[cfg(feature = "external-kernel")]
+pub use kernel::{BRepBackend, ExternalKernel};
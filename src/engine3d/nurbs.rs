@@ -4,6 +4,8 @@
 //! for curve and surface evaluation, derivatives, and manipulation.
 
 use crate::core::{Point3, Vector3, Point2, EPSILON};
+use crate::geometry::surface::ParametricSurface;
+use nalgebra::Unit;
 use serde::{Deserialize, Serialize};
 
 
@@ -498,6 +500,48 @@ impl NurbsSurface {
     }
 }
 
+impl ParametricSurface for NurbsSurface {
+    /// Evaluate at parameters clamped to the knot range; degenerate points
+    /// come back as NaN
+    fn evaluate(&self, u: f64, v: f64) -> Point3 {
+        let ((u0, u1), (v0, v1)) = self.parameter_range();
+        NurbsSurface::evaluate(self, u.clamp(u0, u1), v.clamp(v0, v1))
+            .unwrap_or_else(|_| Point3::new(f64::NAN, f64::NAN, f64::NAN))
+    }
+
+    fn normal(&self, u: f64, v: f64) -> Unit<Vector3> {
+        let normal = self.partial_u(u, v).cross(&self.partial_v(u, v));
+        if normal.norm() < EPSILON {
+            Unit::new_unchecked(Vector3::z())
+        } else {
+            Unit::new_normalize(normal)
+        }
+    }
+
+    fn parameter_range(&self) -> ((f64, f64), (f64, f64)) {
+        (
+            (self.knots_u[0], *self.knots_u.last().unwrap()),
+            (self.knots_v[0], *self.knots_v.last().unwrap()),
+        )
+    }
+
+    /// Finite difference that stays inside the knot range
+    fn partial_u(&self, u: f64, v: f64) -> Vector3 {
+        let ((u0, u1), _) = self.parameter_range();
+        let h = 1e-6 * (u1 - u0);
+        let (a, b) = if u + h > u1 { (u - h, u) } else { (u, u + h) };
+        (ParametricSurface::evaluate(self, b, v) - ParametricSurface::evaluate(self, a, v)) / h
+    }
+
+    /// Finite difference that stays inside the knot range
+    fn partial_v(&self, u: f64, v: f64) -> Vector3 {
+        let (_, (v0, v1)) = self.parameter_range();
+        let h = 1e-6 * (v1 - v0);
+        let (a, b) = if v + h > v1 { (v - h, v) } else { (v, v + h) };
+        (ParametricSurface::evaluate(self, u, b) - ParametricSurface::evaluate(self, u, a)) / h
+    }
+}
+
 /// Tessellated surface mesh
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SurfaceMesh {
@@ -0,0 +1,771 @@
+//! Sheet metal parts: bends, flanges and flat patterns
+//!
+//! A part is a flat base profile in the XY plane, `thickness` thick along
+//! +Z, with flanges bent up or down off its edges and off the far edges of
+//! other flanges. Each bend starts at the edge it is made on, and a
+//! flange's length is the flat web beyond the bend.
+//!
+//! Unfolding uses the bend allowance: the length of the neutral fibre
+//! through the bend, which lies at `K · t` from the inside surface. The
+//! K-factor depends on how tight the bend is relative to the sheet, so it
+//! is looked up by `R / t` in a [`KFactorTable`] unless a flange sets its
+//! own. The flat pattern is the base with a strip of bend allowance plus
+//! flange length laid out beyond each flanged edge; it is exported to DXF
+//! with the outline on [`CUT_LAYER`] and a centerline and note for every
+//! bend on [`BEND_LAYER`].
+//!
+//! Flanges span their whole edge and corners are left open, so flanges on
+//! two edges that meet at a reflex corner would overlap and are rejected.
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use super::mesh::{HalfEdgeMesh, MeshError};
+use super::topology::{ExtrudeOperation, ProfileRegion, RevolveOperation, TopologyError};
+use crate::core::{Point3, Vector3, EPSILON};
+use crate::geometry::Point2D;
+use crate::io::document::{
+    Color, Document, Entity, GeometryType, Layer, Line, LineType, LineWeight, Polyline, Text,
+    TextAlignment, Vec3, Vertex,
+};
+use crate::io::dxf::{DxfVersion, DxfWriter};
+
+/// Layer holding the flat pattern outline in exported DXF
+pub const CUT_LAYER: &str = "CUT";
+
+/// Layer holding bend centerlines and notes in exported DXF
+pub const BEND_LAYER: &str = "BEND";
+
+/// Height of bend notes in exported DXF, in drawing units
+pub const BEND_NOTE_HEIGHT: f64 = 2.5;
+
+/// Largest angle between segments of a folded bend
+const BEND_STEP: f64 = PI / 18.0;
+
+/// K-factors by bend radius over thickness, interpolated linearly between
+/// entries and held constant past either end
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KFactorTable {
+    entries: Vec<(f64, f64)>,
+}
+
+impl KFactorTable {
+    /// Table from `(R / t, K)` pairs in any order
+    pub fn new(mut entries: Vec<(f64, f64)>) -> SheetMetalResult<Self> {
+        if entries.is_empty() {
+            return Err(SheetMetalError::InvalidParameter("empty K-factor table".into()));
+        }
+        if let Some(&(ratio, k)) = entries
+            .iter()
+            .find(|(ratio, k)| !(ratio.is_finite() && *ratio >= 0.0 && (0.0..=1.0).contains(k)))
+        {
+            return Err(SheetMetalError::InvalidParameter(format!(
+                "K-factor {} at R/t {} (K must be in 0..=1, R/t at least 0)",
+                k, ratio
+            )));
+        }
+        entries.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(Self { entries })
+    }
+
+    /// The same K-factor for every bend
+    pub fn constant(k: f64) -> SheetMetalResult<Self> {
+        Self::new(vec![(0.0, k)])
+    }
+
+    /// `(R / t, K)` entries, tightest bend first
+    pub fn entries(&self) -> &[(f64, f64)] {
+        &self.entries
+    }
+
+    /// K-factor for a bend of inner radius `radius` in sheet `thickness` thick
+    pub fn k_factor(&self, radius: f64, thickness: f64) -> f64 {
+        let ratio = radius / thickness;
+        let upper = self.entries.partition_point(|&(r, _)| r <= ratio);
+        match (upper.checked_sub(1).map(|i| self.entries[i]), self.entries.get(upper)) {
+            (Some((r0, k0)), Some(&(r1, k1))) => k0 + (k1 - k0) * (ratio - r0) / (r1 - r0),
+            (Some((_, k)), None) | (None, Some(&(_, k))) => k,
+            (None, None) => 0.5,
+        }
+    }
+}
+
+/// Typical air-bending K-factors for mild steel and aluminium
+impl Default for KFactorTable {
+    fn default() -> Self {
+        Self {
+            entries: vec![
+                (0.0, 0.30),
+                (0.5, 0.33),
+                (1.0, 0.38),
+                (2.0, 0.42),
+                (3.0, 0.45),
+                (5.0, 0.48),
+                (10.0, 0.50),
+            ],
+        }
+    }
+}
+
+/// Length of the neutral fibre through a bend of `angle` radians
+pub fn bend_allowance(angle: f64, radius: f64, thickness: f64, k_factor: f64) -> f64 {
+    angle.abs() * (radius + k_factor * thickness)
+}
+
+/// How much shorter the flat is than the two outside flange dimensions
+/// of a bend measured to the virtual sharp
+pub fn bend_deduction(angle: f64, radius: f64, thickness: f64, k_factor: f64) -> f64 {
+    let setback = (radius + thickness) * (angle.abs() / 2.0).tan();
+    2.0 * setback - bend_allowance(angle, radius, thickness, k_factor)
+}
+
+/// Material and tooling shared by every bend of a part
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SheetMetalRules {
+    /// Sheet thickness
+    pub thickness: f64,
+    /// Inner bend radius used unless a flange sets its own
+    pub bend_radius: f64,
+    /// K-factors used unless a flange sets its own
+    pub k_factors: KFactorTable,
+}
+
+impl SheetMetalRules {
+    /// Rules with the default K-factor table
+    pub fn new(thickness: f64, bend_radius: f64) -> Self {
+        Self {
+            thickness,
+            bend_radius,
+            k_factors: KFactorTable::default(),
+        }
+    }
+}
+
+/// Edge a flange is bent off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FlangeEdge {
+    /// Edge from corner `i` to corner `i + 1` of the base profile
+    Base(usize),
+    /// Far edge of the flange with this index
+    Flange(usize),
+}
+
+/// Bend direction relative to the face the flange is made from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BendDirection {
+    /// Towards the thickness side, +Z for flanges off the base
+    Up,
+    /// Away from the thickness side
+    Down,
+}
+
+impl BendDirection {
+    fn sign(self) -> f64 {
+        match self {
+            BendDirection::Up => 1.0,
+            BendDirection::Down => -1.0,
+        }
+    }
+}
+
+/// Flange bent off an edge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Flange {
+    /// Edge it is bent off
+    pub edge: FlangeEdge,
+    /// Flat length beyond the bend
+    pub length: f64,
+    /// Bend angle in radians, below π
+    pub angle: f64,
+    /// Bend direction
+    pub direction: BendDirection,
+    /// Inner bend radius, the part's when `None`
+    pub radius: Option<f64>,
+    /// K-factor, looked up in the part's table when `None`
+    pub k_factor: Option<f64>,
+}
+
+impl Flange {
+    /// Flange with the part's bend radius and K-factors
+    pub fn new(edge: FlangeEdge, length: f64, angle: f64, direction: BendDirection) -> Self {
+        Self {
+            edge,
+            length,
+            angle,
+            direction,
+            radius: None,
+            k_factor: None,
+        }
+    }
+}
+
+/// Resolved bend of a flange
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bend {
+    /// Index of the flange
+    pub flange: usize,
+    /// Bend angle in radians
+    pub angle: f64,
+    /// Bend direction
+    pub direction: BendDirection,
+    /// Inner bend radius
+    pub radius: f64,
+    /// K-factor
+    pub k_factor: f64,
+    /// Length of the bend in the flat
+    pub allowance: f64,
+    /// Bend deduction
+    pub deduction: f64,
+}
+
+/// Bend as laid out in the flat pattern
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BendLine {
+    /// Centerline start
+    pub start: Point2D,
+    /// Centerline end
+    pub end: Point2D,
+    /// The bend
+    pub bend: Bend,
+}
+
+/// Unfolded part, ready for cutting
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlatPattern {
+    /// Counter-clockwise outline without a repeated closing point
+    pub outline: Vec<Point2D>,
+    /// Bends, in flange order
+    pub bend_lines: Vec<BendLine>,
+    /// Sheet thickness
+    pub thickness: f64,
+}
+
+impl FlatPattern {
+    /// Area of sheet the part uses
+    pub fn area(&self) -> f64 {
+        signed_area(&self.outline)
+    }
+
+    /// Outline and bends as a drawing
+    pub fn to_document(&self) -> Document {
+        let mut doc = Document::new();
+        doc.add_layer(layer(CUT_LAYER, Color::red(), LineType::Continuous));
+        doc.add_layer(layer(BEND_LAYER, Color::new(0, 255, 255), LineType::DashDot));
+
+        let vertices = self
+            .outline
+            .iter()
+            .map(|p| Vertex {
+                position: Vec3::new(p.x, p.y, 0.0),
+                bulge: 0.0,
+            })
+            .collect();
+        doc.add_entity(Entity::new(
+            GeometryType::Polyline(Polyline { vertices, closed: true }),
+            CUT_LAYER.to_string(),
+        ));
+
+        for line in &self.bend_lines {
+            let bend = &line.bend;
+            let mut entity = Entity::new(
+                GeometryType::Line(Line {
+                    start: Vec3::new(line.start.x, line.start.y, 0.0),
+                    end: Vec3::new(line.end.x, line.end.y, 0.0),
+                }),
+                BEND_LAYER.to_string(),
+            );
+            let attributes = [
+                ("bend_angle", format!("{:.2}", bend.angle.to_degrees())),
+                ("bend_direction", format!("{:?}", bend.direction).to_uppercase()),
+                ("bend_radius", format!("{}", bend.radius)),
+                ("k_factor", format!("{:.3}", bend.k_factor)),
+            ];
+            for (key, value) in attributes {
+                entity.attributes.insert(key.to_string(), value);
+            }
+            doc.add_entity(entity);
+
+            let middle = line.start.midpoint(&line.end);
+            doc.add_entity(Entity::new(
+                GeometryType::Text(Text {
+                    position: Vec3::new(middle.x, middle.y, 0.0),
+                    text: format!(
+                        "{} {:.0}° R{}",
+                        format!("{:?}", bend.direction).to_uppercase(),
+                        bend.angle.to_degrees(),
+                        bend.radius
+                    ),
+                    height: BEND_NOTE_HEIGHT,
+                    rotation: (line.end.y - line.start.y).atan2(line.end.x - line.start.x),
+                    style: "STANDARD".to_string(),
+                    horizontal_alignment: TextAlignment::Center,
+                    vertical_alignment: TextAlignment::Bottom,
+                }),
+                BEND_LAYER.to_string(),
+            ));
+        }
+        doc
+    }
+
+    /// Flat pattern as DXF text
+    pub fn to_dxf(&self) -> SheetMetalResult<String> {
+        let mut buffer = Vec::new();
+        DxfWriter::new(DxfVersion::R2000)
+            .write(&self.to_document(), &mut buffer)
+            .map_err(|e| SheetMetalError::Export(e.to_string()))?;
+        String::from_utf8(buffer).map_err(|e| SheetMetalError::Export(e.to_string()))
+    }
+}
+
+/// Sheet metal part: a base profile and the flanges bent off it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SheetMetalPart {
+    rules: SheetMetalRules,
+    base: Vec<Point2D>,
+    flanges: Vec<Flange>,
+}
+
+impl SheetMetalPart {
+    /// Part with a flat base; the profile may be wound either way
+    pub fn new(rules: SheetMetalRules, base: Vec<Point2D>) -> SheetMetalResult<Self> {
+        if !(rules.thickness > EPSILON && rules.thickness.is_finite()) {
+            return Err(SheetMetalError::InvalidParameter(format!("thickness {}", rules.thickness)));
+        }
+        check_radius(rules.bend_radius)?;
+
+        let mut base = base;
+        if base.len() > 1 && base[0].approx_eq(&base[base.len() - 1]) {
+            base.pop();
+        }
+        let area = signed_area(&base);
+        if base.len() < 3 || area.abs() < EPSILON {
+            return Err(SheetMetalError::InvalidProfile);
+        }
+        if area < 0.0 {
+            base.reverse();
+        }
+        Ok(Self {
+            rules,
+            base,
+            flanges: Vec::new(),
+        })
+    }
+
+    /// Material and tooling
+    pub fn rules(&self) -> &SheetMetalRules {
+        &self.rules
+    }
+
+    /// Base profile, counter-clockwise
+    pub fn base(&self) -> &[Point2D] {
+        &self.base
+    }
+
+    /// Flanges in the order they were added
+    pub fn flanges(&self) -> &[Flange] {
+        &self.flanges
+    }
+
+    /// Add a flange, returning its index for flanges bent off its far edge
+    pub fn add_flange(&mut self, flange: Flange) -> SheetMetalResult<usize> {
+        let exists = match flange.edge {
+            FlangeEdge::Base(i) => i < self.base.len(),
+            FlangeEdge::Flange(i) => i < self.flanges.len(),
+        };
+        if !exists {
+            return Err(SheetMetalError::UnknownEdge(flange.edge));
+        }
+        if self.flanges.iter().any(|f| f.edge == flange.edge) {
+            return Err(SheetMetalError::EdgeTaken(flange.edge));
+        }
+        if !(flange.length > 0.0 && flange.length.is_finite()) {
+            return Err(SheetMetalError::InvalidParameter(format!("flange length {}", flange.length)));
+        }
+        if !(flange.angle > EPSILON && flange.angle < PI - EPSILON) {
+            return Err(SheetMetalError::InvalidParameter(format!(
+                "bend angle {:.2}° (must be between 0° and 180°)",
+                flange.angle.to_degrees()
+            )));
+        }
+        if let Some(radius) = flange.radius {
+            check_radius(radius)?;
+        }
+        if let Some(k) = flange.k_factor.filter(|k| !(0.0..=1.0).contains(k)) {
+            return Err(SheetMetalError::InvalidParameter(format!("K-factor {}", k)));
+        }
+
+        if let FlangeEdge::Base(edge) = flange.edge {
+            let n = self.base.len();
+            for (first, second) in [((edge + n - 1) % n, edge), (edge, (edge + 1) % n)] {
+                let other = if first == edge { second } else { first };
+                let taken = self.flanges.iter().any(|f| f.edge == FlangeEdge::Base(other));
+                if taken && self.corner_turn(first, second) < -EPSILON {
+                    return Err(SheetMetalError::FlangesOverlap(first, second));
+                }
+            }
+        }
+
+        self.flanges.push(flange);
+        Ok(self.flanges.len() - 1)
+    }
+
+    /// Resolved bend of a flange
+    pub fn bend(&self, flange: usize) -> Option<Bend> {
+        let f = self.flanges.get(flange)?;
+        let t = self.rules.thickness;
+        let radius = f.radius.unwrap_or(self.rules.bend_radius);
+        let k_factor = f.k_factor.unwrap_or_else(|| self.rules.k_factors.k_factor(radius, t));
+        Some(Bend {
+            flange,
+            angle: f.angle,
+            direction: f.direction,
+            radius,
+            k_factor,
+            allowance: bend_allowance(f.angle, radius, t, k_factor),
+            deduction: bend_deduction(f.angle, radius, t, k_factor),
+        })
+    }
+
+    /// Bends of every flange, in flange order
+    pub fn bends(&self) -> Vec<Bend> {
+        (0..self.flanges.len()).filter_map(|i| self.bend(i)).collect()
+    }
+
+    /// Unfold the part
+    pub fn flat_pattern(&self) -> FlatPattern {
+        let mut outline = Vec::new();
+        let mut bend_lines = Vec::new();
+        let n = self.base.len();
+        for i in 0..n {
+            let (p, q) = (self.base[i], self.base[(i + 1) % n]);
+            self.unfold_edge(p, q, FlangeEdge::Base(i), &mut outline, &mut bend_lines);
+        }
+        bend_lines.sort_by_key(|line| line.bend.flange);
+        FlatPattern {
+            outline,
+            bend_lines,
+            thickness: self.rules.thickness,
+        }
+    }
+
+    /// Folded part as a solid, one closed shell for the base and for each
+    /// bend and flange
+    pub fn fold(&self) -> SheetMetalResult<HalfEdgeMesh> {
+        let t = self.rules.thickness;
+        let base = Frame {
+            origin: Point3::origin(),
+            ex: Vector3::x(),
+            ey: Vector3::y(),
+            w: Vector3::z(),
+        };
+        let mut solid = HalfEdgeMesh::new();
+        let profile: Vec<_> = self.base.iter().map(|p| base.map(*p)).collect();
+        let slab = ExtrudeOperation {
+            direction: base.w * t,
+            ..Default::default()
+        };
+        append(&mut solid, &slab.extrude_profile(&profile)?)?;
+
+        let n = self.base.len();
+        for i in 0..n {
+            let (p, q) = (self.base[i], self.base[(i + 1) % n]);
+            self.fold_edge(&base, p, q, FlangeEdge::Base(i), &mut solid)?;
+        }
+        Ok(solid)
+    }
+
+    /// Outline from `p` along the edge to just before `q`, going around any
+    /// flange on the edge
+    fn unfold_edge(
+        &self,
+        p: Point2D,
+        q: Point2D,
+        edge: FlangeEdge,
+        outline: &mut Vec<Point2D>,
+        bend_lines: &mut Vec<BendLine>,
+    ) {
+        outline.push(p);
+        let Some((index, flange)) = self.flange_on(edge) else {
+            return;
+        };
+        let Some(bend) = self.bend(index) else {
+            return;
+        };
+        let n = outward(p, q);
+        bend_lines.push(BendLine {
+            start: p + n * (bend.allowance / 2.0),
+            end: q + n * (bend.allowance / 2.0),
+            bend,
+        });
+        let reach = n * (bend.allowance + flange.length);
+        self.unfold_edge(p + reach, q + reach, FlangeEdge::Flange(index), outline, bend_lines);
+        outline.push(q + reach);
+    }
+
+    /// Add the bend and flange on an edge of the piece laid out by `frame`,
+    /// and everything bent off that flange
+    fn fold_edge(
+        &self,
+        frame: &Frame,
+        p: Point2D,
+        q: Point2D,
+        edge: FlangeEdge,
+        solid: &mut HalfEdgeMesh,
+    ) -> SheetMetalResult<()> {
+        let Some((index, flange)) = self.flange_on(edge) else {
+            return Ok(());
+        };
+        let Some(bend) = self.bend(index) else {
+            return Ok(());
+        };
+        let t = self.rules.thickness;
+        let d = (q - p).normalize();
+        let n = outward(p, q);
+        let along = frame.ex * d.x + frame.ey * d.y;
+        let out = frame.ex * n.x + frame.ey * n.y;
+        let w = frame.w;
+
+        // The bend turns about an axis on the inside of the bend, parallel
+        // to the edge; up bends have their inside on the thickness side
+        let sign = bend.direction.sign();
+        let offset = if sign > 0.0 { t + bend.radius } else { -bend.radius };
+        let (start, end) = (frame.map(p), frame.map(q));
+        let section = vec![start, end, end + w * t, start + w * t];
+        let revolve = RevolveOperation {
+            axis_origin: start + w * offset,
+            axis_direction: along,
+            angle: -sign * bend.angle,
+            segments: ((bend.angle / BEND_STEP).ceil() as usize).max(3),
+        };
+        append(solid, &revolve.revolve_region(&ProfileRegion::new(section))?)?;
+
+        let (sin, cos) = bend.angle.sin_cos();
+        let out_after = out * cos + w * (sign * sin);
+        let w_after = w * cos - out * (sign * sin);
+        let flat_start = p + n * bend.allowance;
+        let ex = along * d.x + out_after * n.x;
+        let ey = along * d.y + out_after * n.y;
+        let corner = start + w * (offset * (1.0 - cos)) + out * (sign * offset * sin);
+        let next = Frame {
+            origin: corner - ex * flat_start.x - ey * flat_start.y,
+            ex,
+            ey,
+            w: w_after,
+        };
+
+        let reach = n * flange.length;
+        let flat_end = q + n * bend.allowance;
+        let web: Vec<_> = [flat_start, flat_end, flat_end + reach, flat_start + reach]
+            .iter()
+            .map(|f| next.map(*f))
+            .collect();
+        let slab = ExtrudeOperation {
+            direction: w_after * t,
+            ..Default::default()
+        };
+        append(solid, &slab.extrude_profile(&web)?)?;
+
+        self.fold_edge(&next, flat_start + reach, flat_end + reach, FlangeEdge::Flange(index), solid)
+    }
+
+    /// Flange on an edge, with its index
+    fn flange_on(&self, edge: FlangeEdge) -> Option<(usize, &Flange)> {
+        self.flanges.iter().enumerate().find(|(_, f)| f.edge == edge)
+    }
+
+    /// Cross product of base edge `first` with the following edge `second`;
+    /// negative at reflex corners
+    fn corner_turn(&self, first: usize, second: usize) -> f64 {
+        let n = self.base.len();
+        let a = self.base[(first + 1) % n] - self.base[first];
+        let b = self.base[(second + 1) % n] - self.base[second];
+        a.cross(&b)
+    }
+}
+
+/// Affine map from flat pattern coordinates onto one piece of the folded
+/// part, with the direction its thickness grows in
+struct Frame {
+    origin: Point3,
+    ex: Vector3,
+    ey: Vector3,
+    w: Vector3,
+}
+
+impl Frame {
+    fn map(&self, p: Point2D) -> Point3 {
+        self.origin + self.ex * p.x + self.ey * p.y
+    }
+}
+
+/// Unit normal pointing out of a counter-clockwise profile across edge `p`→`q`
+fn outward(p: Point2D, q: Point2D) -> Point2D {
+    let d = (q - p).normalize();
+    Point2D::new(d.y, -d.x)
+}
+
+fn signed_area(points: &[Point2D]) -> f64 {
+    let n = points.len();
+    (0..n).map(|i| points[i].cross(&points[(i + 1) % n])).sum::<f64>() / 2.0
+}
+
+fn check_radius(radius: f64) -> SheetMetalResult<()> {
+    if radius > EPSILON && radius.is_finite() {
+        Ok(())
+    } else {
+        Err(SheetMetalError::InvalidParameter(format!("bend radius {}", radius)))
+    }
+}
+
+fn layer(name: &str, color: Color, line_type: LineType) -> Layer {
+    Layer {
+        name: name.to_string(),
+        color,
+        line_type,
+        line_weight: LineWeight::Default,
+        visible: true,
+        locked: false,
+        frozen: false,
+        plottable: true,
+        confidential: false,
+    }
+}
+
+/// Copy the faces of `source` into `target` as a separate shell
+fn append(target: &mut HalfEdgeMesh, source: &HalfEdgeMesh) -> SheetMetalResult<()> {
+    let mut vertices = HashMap::new();
+    for vh in source.vertex_handles() {
+        let position = source.get_vertex(vh)?.position;
+        vertices.insert(vh, target.add_vertex(position));
+    }
+    for fh in source.face_handles() {
+        let face: Vec<_> = source.face_vertices(fh)?.iter().map(|vh| vertices[vh]).collect();
+        target.add_face(&face)?;
+    }
+    Ok(())
+}
+
+/// Sheet metal errors
+#[derive(Debug, thiserror::Error)]
+pub enum SheetMetalError {
+    #[error("Base profile needs at least three corners and a nonzero area")]
+    InvalidProfile,
+
+    #[error("Invalid sheet metal parameter: {0}")]
+    InvalidParameter(String),
+
+    #[error("No edge {0:?} to bend a flange off")]
+    UnknownEdge(FlangeEdge),
+
+    #[error("Edge {0:?} already has a flange")]
+    EdgeTaken(FlangeEdge),
+
+    #[error("Flanges on base edges {0} and {1} would overlap at their reflex corner")]
+    FlangesOverlap(usize, usize),
+
+    #[error(transparent)]
+    Topology(#[from] TopologyError),
+
+    #[error(transparent)]
+    Mesh(#[from] MeshError),
+
+    #[error("DXF export failed: {0}")]
+    Export(String),
+}
+
+pub type SheetMetalResult<T> = Result<T, SheetMetalError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine3d::analysis::GeometryAnalyzer;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn rect(w: f64, h: f64) -> Vec<Point2D> {
+        vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(w, 0.0),
+            Point2D::new(w, h),
+            Point2D::new(0.0, h),
+        ]
+    }
+
+    #[test]
+    fn test_bend_math() {
+        let table = KFactorTable::default();
+        assert!((table.k_factor(1.0, 1.0) - 0.38).abs() < 1e-12);
+        assert!((table.k_factor(1.5, 1.0) - 0.40).abs() < 1e-12);
+        assert!((table.k_factor(40.0, 1.0) - 0.50).abs() < 1e-12);
+        assert!(KFactorTable::new(vec![(1.0, 1.5)]).is_err());
+
+        // 90° bend, R = t = 1, K = 0.5: BA = π/2 · 1.5, BD = 2 · 2 − BA
+        let allowance = bend_allowance(FRAC_PI_2, 1.0, 1.0, 0.5);
+        assert!((allowance - 0.75 * PI).abs() < 1e-12);
+        assert!((bend_deduction(FRAC_PI_2, 1.0, 1.0, 0.5) - (4.0 - allowance)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_tray_flat_pattern_and_fold() {
+        let rules = SheetMetalRules {
+            thickness: 1.0,
+            bend_radius: 2.0,
+            k_factors: KFactorTable::constant(0.4).unwrap(),
+        };
+        // Clockwise input is rewound
+        let base: Vec<_> = rect(100.0, 60.0).into_iter().rev().collect();
+        let mut part = SheetMetalPart::new(rules, base).unwrap();
+        let front = part
+            .add_flange(Flange::new(FlangeEdge::Base(0), 20.0, FRAC_PI_2, BendDirection::Up))
+            .unwrap();
+        part.add_flange(Flange::new(FlangeEdge::Base(2), 20.0, FRAC_PI_2, BendDirection::Up))
+            .unwrap();
+        // Return lip off the front flange, bent back over the tray
+        part.add_flange(Flange::new(FlangeEdge::Flange(front), 10.0, FRAC_PI_2, BendDirection::Up))
+            .unwrap();
+        assert!(matches!(
+            part.add_flange(Flange::new(FlangeEdge::Base(0), 5.0, FRAC_PI_2, BendDirection::Down)),
+            Err(SheetMetalError::EdgeTaken(_))
+        ));
+        assert!(matches!(
+            part.add_flange(Flange::new(FlangeEdge::Flange(9), 5.0, FRAC_PI_2, BendDirection::Up)),
+            Err(SheetMetalError::UnknownEdge(_))
+        ));
+
+        // Each bend adds π/2 · (2 + 0.4) of flat length
+        let allowance = FRAC_PI_2 * 2.4;
+        let flat = part.flat_pattern();
+        assert_eq!(flat.outline.len(), 10);
+        assert_eq!(flat.bend_lines.len(), 3);
+        let strip = 60.0 + 3.0 * allowance + 20.0 + 20.0 + 10.0;
+        assert!((flat.area() - 100.0 * strip).abs() < 1e-9);
+        let first = &flat.bend_lines[0];
+        assert!((first.start.y + allowance / 2.0).abs() < 1e-9);
+        assert!((first.bend.deduction - (2.0 * 3.0 - allowance)).abs() < 1e-9);
+
+        // The folded solid holds the base, webs and bend sectors
+        let solid = part.fold().unwrap();
+        let props = GeometryAnalyzer::compute_mass_properties(&solid, 1.0).unwrap();
+        let sector = FRAC_PI_2 * 1.0 * 2.5 * 100.0;
+        let expected = 100.0 * 60.0 + 3.0 * sector + 100.0 * 50.0;
+        assert!((props.volume - expected).abs() / expected < 0.01);
+        // Front flange stands up outside y = 0 and the lip reaches back in
+        assert!((props.bbox_min.y + 3.0).abs() < 1e-6);
+        assert!((props.bbox_max.y - 63.0).abs() < 1e-6);
+        assert!((props.bbox_max.z - (3.0 + 20.0 + 3.0)).abs() < 1e-6);
+
+        // A down flange hangs below the base, its outside still at y = -3
+        let mut hem = SheetMetalPart::new(SheetMetalRules::new(1.0, 2.0), rect(100.0, 60.0)).unwrap();
+        hem.add_flange(Flange::new(FlangeEdge::Base(0), 20.0, FRAC_PI_2, BendDirection::Down))
+            .unwrap();
+        let props = GeometryAnalyzer::compute_mass_properties(&hem.fold().unwrap(), 1.0).unwrap();
+        assert!((props.bbox_min.y + 3.0).abs() < 1e-6);
+        assert!((props.bbox_min.z + 22.0).abs() < 1e-6);
+
+        let dxf = flat.to_dxf().unwrap();
+        assert!(dxf.contains(CUT_LAYER));
+        assert!(dxf.contains(BEND_LAYER));
+        assert!(dxf.contains("UP 90° R2"));
+    }
+}
@@ -1,40 +1,28 @@
 //! Adaptive tessellation algorithms for converting curved surfaces to triangles
 //!
-//! Provides enterprise-grade tessellation with adaptive refinement based on
-//! curvature, screen-space error, and user-defined quality settings.
+//! Surfaces start from a parameter grid through their knots, with as many
+//! samples per knot span as the degree. Rows and columns are then split
+//! independently: a span is halved only where an edge across it breaks the
+//! chord, angle or length limits of the [`TessellationSettings`]. A
+//! cylinder is refined around its axis but keeps one span along it and a
+//! bilinear patch stays two triangles, so exports stay small while close-up
+//! views stay smooth. Rows and columns run across the whole patch, so
+//! neighbouring facets always share their vertices and the mesh has no
+//! cracks.
 
 use super::mesh::{HalfEdgeMesh, VertexHandle};
 use super::nurbs::{NurbsSurface, NurbsCurve};
 use crate::core::{Point3, Vector3, Point2, EPSILON};
-use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-/// Tessellation quality settings
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub struct TessellationSettings {
-    /// Maximum chord error (distance from curve to chord)
-    pub max_chord_error: f64,
-    /// Maximum angle between adjacent face normals (in radians)
-    pub max_angle_deviation: f64,
-    /// Minimum edge length
-    pub min_edge_length: f64,
-    /// Maximum edge length
-    pub max_edge_length: f64,
-    /// Target number of triangles (soft limit)
-    pub target_triangle_count: Option<usize>,
-}
+pub use crate::geometry::tessellate::TessellationSettings;
 
-impl Default for TessellationSettings {
-    fn default() -> Self {
-        Self {
-            max_chord_error: 0.01,
-            max_angle_deviation: 0.1, // ~5.7 degrees
-            min_edge_length: 0.001,
-            max_edge_length: 1.0,
-            target_triangle_count: None,
-        }
-    }
-}
+/// Refinement passes, each halving at most every span
+const MAX_REFINEMENTS: usize = 12;
+/// Deepest bisection of one curve span
+const MAX_CURVE_DEPTH: usize = 20;
+/// Parameter step of finite difference normals
+const NORMAL_STEP: f64 = 1e-6;
 
 /// Adaptive tessellator for NURBS surfaces
 pub struct AdaptiveTessellator {
@@ -47,166 +35,185 @@ impl AdaptiveTessellator {
         Self { settings }
     }
 
+    /// Tolerances the tessellator refines to
+    pub fn settings(&self) -> &TessellationSettings {
+        &self.settings
+    }
+
     /// Tessellate a NURBS surface adaptively
     pub fn tessellate_surface(&self, surface: &NurbsSurface) -> Result<HalfEdgeMesh, TessellationError> {
-        // Start with a coarse grid
-        let initial_u = 4;
-        let initial_v = 4;
-
-        // Generate initial parameter grid
-        let mut grid = ParameterGrid::new(initial_u, initial_v);
-
-        // Refine adaptively based on curvature and error
+        let mut grid = ParameterGrid {
+            u_params: seed_params(&surface.knots_u, surface.degree_u),
+            v_params: seed_params(&surface.knots_v, surface.degree_v),
+        };
         self.refine_grid(surface, &mut grid)?;
-
-        // Convert grid to mesh
         self.grid_to_mesh(surface, &grid)
     }
 
-    /// Refine the parameter grid adaptively
+    /// Split grid spans until every edge meets the tolerances, the pass
+    /// limit is reached or the next pass would pass the triangle target
     fn refine_grid(&self, surface: &NurbsSurface, grid: &mut ParameterGrid) -> Result<(), TessellationError> {
-        let max_iterations = 10;
-        let mut iteration = 0;
-
-        loop {
-            if iteration >= max_iterations {
+        for _ in 0..MAX_REFINEMENTS {
+            let (split_u, split_v) = self.spans_to_split(surface, grid)?;
+            if split_u.is_empty() && split_v.is_empty() {
                 break;
             }
-
-            let mut needs_refinement = Vec::new();
-
-            // Check each quad for refinement criteria
-            for i in 0..grid.num_u - 1 {
-                for j in 0..grid.num_v - 1 {
-                    if self.needs_refinement(surface, grid, i, j)? {
-                        needs_refinement.push((i, j));
-                    }
+            if let Some(target) = self.settings.target_triangle_count {
+                let rows = grid.u_params.len() + split_u.len() - 1;
+                let columns = grid.v_params.len() + split_v.len() - 1;
+                if 2 * rows * columns > target {
+                    break;
                 }
             }
-
-            if needs_refinement.is_empty() {
-                break;
-            }
-
-            // Refine marked quads
-            grid.refine_quads(&needs_refinement);
-
-            iteration += 1;
+            grid.split(&split_u, &split_v);
         }
-
         Ok(())
     }
 
-    /// Check if a quad needs refinement
-    fn needs_refinement(
+    /// Indices of the u and v spans that some edge across is too coarse for
+    fn spans_to_split(
         &self,
         surface: &NurbsSurface,
         grid: &ParameterGrid,
-        i: usize,
-        j: usize,
-    ) -> Result<bool, TessellationError> {
-        let u0 = grid.u_params[i];
-        let u1 = grid.u_params[i + 1];
-        let v0 = grid.v_params[j];
-        let v1 = grid.v_params[j + 1];
-
-        let u_mid = (u0 + u1) / 2.0;
-        let v_mid = (v0 + v1) / 2.0;
-
-        // Evaluate corners and center
-        let p00 = surface.evaluate(u0, v0).map_err(|_| TessellationError::EvaluationFailed)?;
-        let p01 = surface.evaluate(u0, v1).map_err(|_| TessellationError::EvaluationFailed)?;
-        let p10 = surface.evaluate(u1, v0).map_err(|_| TessellationError::EvaluationFailed)?;
-        let p11 = surface.evaluate(u1, v1).map_err(|_| TessellationError::EvaluationFailed)?;
-        let p_mid = surface.evaluate(u_mid, v_mid).map_err(|_| TessellationError::EvaluationFailed)?;
-
-        // Compute bilinear interpolation at center
-        let p_interp = Point3::from(
-            0.25 * (p00.coords + p01.coords + p10.coords + p11.coords)
-        );
-
-        // Check chord error
-        let chord_error = (p_mid - p_interp).norm();
-        if chord_error > self.settings.max_chord_error {
-            return Ok(true);
+    ) -> Result<(Vec<usize>, Vec<usize>), TessellationError> {
+        let (nu, nv) = (grid.u_params.len(), grid.v_params.len());
+        let mut points = Vec::with_capacity(nu * nv);
+        let mut normals = Vec::with_capacity(nu * nv);
+        for &u in &grid.u_params {
+            for &v in &grid.v_params {
+                points.push(evaluate(surface, u, v)?);
+                normals.push(self.compute_normal(surface, u, v)?);
+            }
         }
-
-        // Check edge lengths
-        let edge_lengths = [
-            (p01 - p00).norm(),
-            (p11 - p10).norm(),
-            (p10 - p00).norm(),
-            (p11 - p01).norm(),
-        ];
-
-        for &length in &edge_lengths {
-            if length > self.settings.max_edge_length {
-                return Ok(true);
+        let at = |i: usize, j: usize| i * nv + j;
+
+        let mut split_u = vec![false; nu - 1];
+        for (i, split) in split_u.iter_mut().enumerate() {
+            let u_mid = 0.5 * (grid.u_params[i] + grid.u_params[i + 1]);
+            for (j, &v) in grid.v_params.iter().enumerate() {
+                let (a, b) = (at(i, j), at(i + 1, j));
+                let mid = evaluate(surface, u_mid, v)?;
+                if self.too_coarse(points[a], mid, points[b], normals[a], normals[b]) {
+                    *split = true;
+                    break;
+                }
+            }
+        }
+        let mut split_v = vec![false; nv - 1];
+        for (j, split) in split_v.iter_mut().enumerate() {
+            let v_mid = 0.5 * (grid.v_params[j] + grid.v_params[j + 1]);
+            for (i, &u) in grid.u_params.iter().enumerate() {
+                let (a, b) = (at(i, j), at(i, j + 1));
+                let mid = evaluate(surface, u, v_mid)?;
+                if self.too_coarse(points[a], mid, points[b], normals[a], normals[b]) {
+                    *split = true;
+                    break;
+                }
             }
         }
 
-        // Check angle deviation (curvature)
-        let n00 = self.compute_normal(surface, u0, v0)?;
-        let n11 = self.compute_normal(surface, u1, v1)?;
-        let angle = n00.dot(&n11).acos();
-
-        if angle > self.settings.max_angle_deviation {
-            return Ok(true);
+        // Twist inside a quad shows in none of its edges
+        for (i, j) in (0..nu - 1).flat_map(|i| (0..nv - 1).map(move |j| (i, j))) {
+            if split_u[i] || split_v[j] {
+                continue;
+            }
+            let [p00, p10, p01, p11] =
+                [at(i, j), at(i + 1, j), at(i, j + 1), at(i + 1, j + 1)].map(|k| points[k]);
+            let bilinear = Point3::from((p00.coords + p10.coords + p01.coords + p11.coords) / 4.0);
+            let u_mid = 0.5 * (grid.u_params[i] + grid.u_params[i + 1]);
+            let v_mid = 0.5 * (grid.v_params[j] + grid.v_params[j + 1]);
+            let center = evaluate(surface, u_mid, v_mid)?;
+            if (center - bilinear).norm() > self.settings.max_chord_error {
+                let min_span = 2.0 * self.settings.min_edge_length;
+                split_u[i] = (p10 - p00).norm().max((p11 - p01).norm()) >= min_span;
+                split_v[j] = (p01 - p00).norm().max((p11 - p10).norm()) >= min_span;
+            }
         }
 
-        Ok(false)
+        let marked = |splits: Vec<bool>| (0..splits.len()).filter(|&i| splits[i]).collect();
+        Ok((marked(split_u), marked(split_v)))
     }
 
-    /// Compute surface normal at (u, v)
-    fn compute_normal(&self, surface: &NurbsSurface, u: f64, v: f64) -> Result<Vector3, TessellationError> {
-        let du = 1e-6;
-        let dv = 1e-6;
+    /// Whether the straight edge from `a` to `b` strays too far from the
+    /// surface through `mid`, turns too sharply or is too long; edges are
+    /// never split below twice the minimum edge length
+    fn too_coarse(
+        &self,
+        a: Point3,
+        mid: Point3,
+        b: Point3,
+        normal_a: Option<Vector3>,
+        normal_b: Option<Vector3>,
+    ) -> bool {
+        let length = (mid - a).norm() + (b - mid).norm();
+        if length < 2.0 * self.settings.min_edge_length {
+            return false;
+        }
+        let chord_mid = Point3::from((a.coords + b.coords) / 2.0);
+        let turn = match (normal_a, normal_b) {
+            (Some(na), Some(nb)) => na.dot(&nb).clamp(-1.0, 1.0).acos(),
+            _ => 0.0,
+        };
+        (mid - chord_mid).norm() > self.settings.max_chord_error
+            || turn > self.settings.max_angle_deviation
+            || self.settings.max_edge_length.is_some_and(|max| length > max)
+    }
 
-        let p = surface.evaluate(u, v).map_err(|_| TessellationError::EvaluationFailed)?;
-        let pu = surface.evaluate(u + du, v).map_err(|_| TessellationError::EvaluationFailed)?;
-        let pv = surface.evaluate(u, v + dv).map_err(|_| TessellationError::EvaluationFailed)?;
+    /// Unit surface normal at (u, v), `None` where the surface degenerates
+    /// to a point such as the pole of a sphere
+    fn compute_normal(
+        &self,
+        surface: &NurbsSurface,
+        u: f64,
+        v: f64,
+    ) -> Result<Option<Vector3>, TessellationError> {
+        // Step backwards at the far end of the domain to stay inside it
+        let step = |t: f64, knots: &[f64]| {
+            if t + NORMAL_STEP <= parameter_range(knots).1 {
+                NORMAL_STEP
+            } else {
+                -NORMAL_STEP
+            }
+        };
+        let (du, dv) = (step(u, &surface.knots_u), step(v, &surface.knots_v));
 
-        let tangent_u = (pu - p) / du;
-        let tangent_v = (pv - p) / dv;
+        let p = evaluate(surface, u, v)?;
+        let tangent_u = (evaluate(surface, u + du, v)? - p) / du;
+        let tangent_v = (evaluate(surface, u, v + dv)? - p) / dv;
 
         let normal = tangent_u.cross(&tangent_v);
         let len = normal.norm();
-
-        if len < EPSILON {
-            Ok(Vector3::new(0.0, 0.0, 1.0))
-        } else {
-            Ok(normal / len)
-        }
+        Ok((len >= EPSILON).then(|| normal / len))
     }
 
-    /// Convert parameter grid to mesh
+    /// Convert parameter grid to mesh, leaving out triangles collapsed at
+    /// degenerate edges
     fn grid_to_mesh(&self, surface: &NurbsSurface, grid: &ParameterGrid) -> Result<HalfEdgeMesh, TessellationError> {
         let mut mesh = HalfEdgeMesh::new();
-        let mut vertex_map: HashMap<(usize, usize), VertexHandle> = HashMap::new();
+        let mut vertex_map: HashMap<(usize, usize), (VertexHandle, Point3)> = HashMap::new();
 
         // Create vertices
-        for i in 0..grid.num_u {
-            for j in 0..grid.num_v {
-                let u = grid.u_params[i];
-                let v = grid.v_params[j];
-
-                let point = surface.evaluate(u, v).map_err(|_| TessellationError::EvaluationFailed)?;
-                let vh = mesh.add_vertex(point);
-                vertex_map.insert((i, j), vh);
+        for (i, &u) in grid.u_params.iter().enumerate() {
+            for (j, &v) in grid.v_params.iter().enumerate() {
+                let point = evaluate(surface, u, v)?;
+                vertex_map.insert((i, j), (mesh.add_vertex(point), point));
             }
         }
 
-        // Create faces
-        for i in 0..grid.num_u - 1 {
-            for j in 0..grid.num_v - 1 {
+        // Create faces, two triangles per quad
+        for i in 0..grid.u_params.len() - 1 {
+            for j in 0..grid.v_params.len() - 1 {
                 let v00 = vertex_map[&(i, j)];
                 let v01 = vertex_map[&(i, j + 1)];
                 let v10 = vertex_map[&(i + 1, j)];
                 let v11 = vertex_map[&(i + 1, j + 1)];
 
-                // Create two triangles for the quad
-                mesh.add_face(&[v00, v10, v11]).map_err(|_| TessellationError::MeshCreationFailed)?;
-                mesh.add_face(&[v00, v11, v01]).map_err(|_| TessellationError::MeshCreationFailed)?;
+                for [a, b, c] in [[v00, v10, v11], [v00, v11, v01]] {
+                    if (b.1 - a.1).cross(&(c.1 - a.1)).norm() < EPSILON {
+                        continue;
+                    }
+                    mesh.add_face(&[a.0, b.0, c.0]).map_err(|_| TessellationError::MeshCreationFailed)?;
+                }
             }
         }
 
@@ -217,105 +224,99 @@ impl AdaptiveTessellator {
     }
 
     /// Tessellate a NURBS curve into line segments
+    ///
+    /// Spans are bisected until each segment's chord error, length and turn
+    /// are within tolerance. The curve is split into
+    /// spans at its knots first, each cut into as many pieces as the degree,
+    /// so that an S bend whose midpoint lies on its chord is still refined.
     pub fn tessellate_curve(&self, curve: &NurbsCurve) -> Result<Vec<Point3>, TessellationError> {
-        let mut points = vec![
-            curve.evaluate(0.0).map_err(|_| TessellationError::EvaluationFailed)?
-        ];
-
-        self.tessellate_curve_recursive(curve, 0.0, 1.0, &mut points)?;
-
-        points.push(
-            curve.evaluate(1.0).map_err(|_| TessellationError::EvaluationFailed)?
-        );
+        let params = seed_params(&curve.knots, curve.degree);
+        let mut points = vec![evaluate_curve(curve, params[0])?];
+        for span in params.windows(2) {
+            self.tessellate_curve_recursive(curve, span[0], span[1], 0, &mut points)?;
+        }
 
         Ok(points)
     }
 
-    /// Recursively tessellate a curve segment
+    /// Recursively tessellate a curve segment, pushing every point after
+    /// the start of the span
     fn tessellate_curve_recursive(
         &self,
         curve: &NurbsCurve,
         t0: f64,
         t1: f64,
+        depth: usize,
         points: &mut Vec<Point3>,
     ) -> Result<(), TessellationError> {
-        let p0 = curve.evaluate(t0).map_err(|_| TessellationError::EvaluationFailed)?;
-        let p1 = curve.evaluate(t1).map_err(|_| TessellationError::EvaluationFailed)?;
+        let p0 = *points.last().ok_or(TessellationError::EvaluationFailed)?;
+        let p1 = evaluate_curve(curve, t1)?;
         let t_mid = (t0 + t1) / 2.0;
-        let p_mid = curve.evaluate(t_mid).map_err(|_| TessellationError::EvaluationFailed)?;
-
-        // Compute chord midpoint
-        let chord_mid = Point3::from((p0.coords + p1.coords) / 2.0);
+        let p_mid = evaluate_curve(curve, t_mid)?;
 
-        // Compute chord error
-        let error = (p_mid - chord_mid).norm();
+        // The halves of a circular span turn through half the span's angle
+        let halves = [p_mid - p0, p1 - p_mid];
+        let turn = match halves.map(|d| d.try_normalize(EPSILON)) {
+            [Some(a), Some(b)] => 2.0 * a.dot(&b).clamp(-1.0, 1.0).acos(),
+            _ => 0.0,
+        };
+        let long_enough = halves[0].norm() + halves[1].norm() >= 2.0 * self.settings.min_edge_length;
+        let coarse = self.too_coarse(p0, p_mid, p1, None, None)
+            || long_enough && turn > self.settings.max_angle_deviation;
 
-        if error > self.settings.max_chord_error && (p1 - p0).norm() > self.settings.min_edge_length {
-            // Subdivide
-            self.tessellate_curve_recursive(curve, t0, t_mid, points)?;
-            points.push(p_mid);
-            self.tessellate_curve_recursive(curve, t_mid, t1, points)?;
+        if depth < MAX_CURVE_DEPTH && coarse {
+            self.tessellate_curve_recursive(curve, t0, t_mid, depth + 1, points)?;
+            self.tessellate_curve_recursive(curve, t_mid, t1, depth + 1, points)?;
+        } else {
+            points.push(p1);
         }
 
         Ok(())
     }
 }
 
-/// Parameter grid for tessellation
-struct ParameterGrid {
-    u_params: Vec<f64>,
-    v_params: Vec<f64>,
-    num_u: usize,
-    num_v: usize,
+/// Parameter domain of a knot vector
+fn parameter_range(knots: &[f64]) -> (f64, f64) {
+    (knots[0], knots[knots.len() - 1])
 }
 
-impl ParameterGrid {
-    /// Create a new parameter grid
-    fn new(num_u: usize, num_v: usize) -> Self {
-        let u_params: Vec<f64> = (0..num_u).map(|i| i as f64 / (num_u - 1) as f64).collect();
-        let v_params: Vec<f64> = (0..num_v).map(|i| i as f64 / (num_v - 1) as f64).collect();
-
-        Self {
-            u_params,
-            v_params,
-            num_u,
-            num_v,
-        }
+/// Distinct knots with each span between them cut into `degree` pieces
+fn seed_params(knots: &[f64], degree: usize) -> Vec<f64> {
+    let mut distinct = knots.to_vec();
+    distinct.dedup_by(|b, a| *b - *a < EPSILON);
+    let pieces = degree.max(1);
+    let mut params = Vec::with_capacity((distinct.len() - 1) * pieces + 1);
+    for span in distinct.windows(2) {
+        params.extend((0..pieces).map(|k| span[0] + (span[1] - span[0]) * k as f64 / pieces as f64));
     }
+    params.push(distinct[distinct.len() - 1]);
+    params
+}
 
-    /// Refine the grid by subdividing marked quads
-    fn refine_quads(&mut self, quads: &[(usize, usize)]) {
-        // Collect unique split points
-        let mut u_splits = std::collections::HashSet::new();
-        let mut v_splits = std::collections::HashSet::new();
-
-        for &(i, j) in quads {
-            let u_mid = (self.u_params[i] + self.u_params[i + 1]) / 2.0;
-            let v_mid = (self.v_params[j] + self.v_params[j + 1]) / 2.0;
+fn evaluate(surface: &NurbsSurface, u: f64, v: f64) -> Result<Point3, TessellationError> {
+    surface.evaluate(u, v).map_err(|_| TessellationError::EvaluationFailed)
+}
 
-            u_splits.insert(ordered_float::OrderedFloat(u_mid));
-            v_splits.insert(ordered_float::OrderedFloat(v_mid));
-        }
+fn evaluate_curve(curve: &NurbsCurve, t: f64) -> Result<Point3, TessellationError> {
+    curve.evaluate(t).map_err(|_| TessellationError::EvaluationFailed)
+}
 
-        // Insert split points
-        for &u in &u_splits {
-            if !self.u_params.contains(&u.0) {
-                self.u_params.push(u.0);
-            }
-        }
+/// Parameter grid for tessellation
+struct ParameterGrid {
+    u_params: Vec<f64>,
+    v_params: Vec<f64>,
+}
 
-        for &v in &v_splits {
-            if !self.v_params.contains(&v.0) {
-                self.v_params.push(v.0);
-            }
+impl ParameterGrid {
+    /// Halve the marked u and v spans
+    fn split(&mut self, u_spans: &[usize], v_spans: &[usize]) {
+        fn halve(params: &mut Vec<f64>, spans: &[usize]) {
+            let mids: Vec<f64> = spans.iter().map(|&i| 0.5 * (params[i] + params[i + 1])).collect();
+            params.extend(mids);
+            params.sort_by(f64::total_cmp);
         }
-
-        // Sort parameters
-        self.u_params.sort_by(|a, b| a.partial_cmp(b).unwrap());
-        self.v_params.sort_by(|a, b| a.partial_cmp(b).unwrap());
-
-        self.num_u = self.u_params.len();
-        self.num_v = self.v_params.len();
+        halve(&mut self.u_params, u_spans);
+        halve(&mut self.v_params, v_spans);
     }
 }
 
@@ -436,6 +437,60 @@ mod tests {
         assert!(settings.max_angle_deviation > 0.0);
     }
 
+    #[test]
+    fn test_adaptive_cylinder() {
+        // Quarter cylinder of radius 10 and height 50: an exact rational
+        // quadratic arc around the axis, straight along it
+        let w = std::f64::consts::FRAC_1_SQRT_2;
+        let arc = [(10.0, 0.0, 1.0), (10.0, 10.0, w), (0.0, 10.0, 1.0)];
+        let control_points = arc
+            .iter()
+            .map(|&(x, y, _)| vec![Point3::new(x, y, 0.0), Point3::new(x, y, 50.0)])
+            .collect();
+        let weights = arc.iter().map(|&(_, _, w)| vec![w, w]).collect();
+        let surface = NurbsSurface::new(2, 1, control_points, weights).unwrap();
+
+        let settings = TessellationSettings::default();
+        let mesh = AdaptiveTessellator::new(settings).tessellate_surface(&surface).unwrap();
+        let stats = mesh.stats();
+        // One span along the axis, and about as many around it as the
+        // chord tolerance needs
+        let around = settings.arc_segments(10.0, std::f64::consts::FRAC_PI_2);
+        assert_eq!(stats.vertices % 2, 0);
+        let spans = stats.vertices / 2 - 1;
+        assert!(spans >= around && spans <= 2 * around);
+        assert_eq!(stats.faces, 2 * spans);
+        for face in mesh.face_handles() {
+            let corners: Vec<Point3> = mesh
+                .face_vertices(face)
+                .unwrap()
+                .into_iter()
+                .map(|v| mesh.get_vertex(v).unwrap().position)
+                .collect();
+            let centroid = corners.iter().map(|p| p.coords).sum::<Vector3>() / 3.0;
+            assert!(10.0 - centroid.xy().norm() <= settings.max_chord_error);
+        }
+
+        // Tighter views refine further
+        let close = AdaptiveTessellator::new(settings.for_pixel_size(0.001))
+            .tessellate_surface(&surface)
+            .unwrap();
+        assert!(close.stats().faces > stats.faces);
+
+        let curve = NurbsCurve::new(
+            2,
+            arc.iter().map(|&(x, y, _)| Point3::new(x, y, 0.0)).collect(),
+            arc.iter().map(|&(_, _, w)| w).collect(),
+        )
+        .unwrap();
+        let points = AdaptiveTessellator::new(settings).tessellate_curve(&curve).unwrap();
+        assert!(points.len() > around && points.len() <= 2 * around + 1);
+        for pair in points.windows(2) {
+            let mid = Point3::from((pair[0].coords + pair[1].coords) / 2.0);
+            assert!(10.0 - mid.coords.norm() <= settings.max_chord_error);
+        }
+    }
+
     #[test]
     fn test_delaunay_triangulation() {
         let points = vec![
@@ -2,14 +2,87 @@
 //!
 //! Implements extrude, revolve, sweep, loft, and other operations
 //! that create 3D geometry from 2D profiles or transform existing geometry.
+//!
+//! Closed profiles become closed solids. A [`ProfileRegion`] may carry holes,
+//! whose caps are triangulated, and loops are rewound as needed so every
+//! solid faces outward whichever way the sketch was drawn.
+//!
+//! Shelling hollows a closed solid to a wall thickness, optionally opening
+//! it through selected faces. Faces whose wall can't be built (too thin or
+//! too tightly curved for the thickness) are reported individually.
 
-use super::mesh::{HalfEdgeMesh, VertexHandle};
+use super::mesh::{FaceHandle, HalfEdgeMesh, VertexHandle};
 use super::nurbs::NurbsCurve;
 use crate::core::{Point3, Vector3, EPSILON};
+use crate::geometry::point::Point2D;
+use crate::geometry::polygon::Polygon2D;
+use crate::geometry::tessellate::tessellate;
 use nalgebra::{Vector3 as NVector3, UnitQuaternion};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::f64::consts::PI;
+use std::fmt;
+
+/// Closed planar profile: an outer boundary and any holes inside it
+///
+/// Loops may be wound either way and may repeat their first point at the end.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProfileRegion {
+    /// Outer boundary
+    pub outer: Vec<Point3>,
+    /// Hole boundaries
+    pub holes: Vec<Vec<Point3>>,
+}
+
+impl ProfileRegion {
+    /// Region without holes
+    pub fn new(outer: Vec<Point3>) -> Self {
+        Self {
+            outer,
+            holes: Vec::new(),
+        }
+    }
+
+    /// Add a hole
+    pub fn with_hole(mut self, hole: Vec<Point3>) -> Self {
+        self.holes.push(hole);
+        self
+    }
+
+    /// Unit normal the outer boundary winds counter-clockwise around
+    pub fn normal(&self) -> Option<Vector3> {
+        let normal = newell_normal(&self.outer);
+        (normal.norm() > EPSILON).then(|| normal.normalize())
+    }
+
+    fn validate(&self) -> Result<(), TopologyError> {
+        if std::iter::once(&self.outer)
+            .chain(&self.holes)
+            .any(|ring| open_ring(ring).len() < 3)
+        {
+            return Err(TopologyError::InsufficientVertices);
+        }
+        Ok(())
+    }
+
+    /// Outer loop counter-clockwise and holes clockwise around `axis`
+    fn oriented(&self, axis: &Vector3) -> Vec<Vec<Point3>> {
+        std::iter::once(wound(&self.outer, axis, true))
+            .chain(self.holes.iter().map(|hole| wound(hole, axis, false)))
+            .collect()
+    }
+}
+
+/// A sketch region in the XY plane, such as one found by the boundary command
+impl From<&Polygon2D> for ProfileRegion {
+    fn from(polygon: &Polygon2D) -> Self {
+        let lift = |ring: &Vec<Point2D>| ring.iter().map(|p| Point3::new(p.x, p.y, 0.0)).collect();
+        Self {
+            outer: lift(&polygon.vertices),
+            holes: polygon.holes.iter().map(lift).collect(),
+        }
+    }
+}
 
 /// Extrude a 2D profile along a direction
 pub struct ExtrudeOperation {
@@ -21,6 +94,9 @@ pub struct ExtrudeOperation {
     pub twist: f64,
     /// Scale factor along extrusion
     pub taper: f64,
+    /// Draft angle in radians; positive angles lean the walls in, shrinking
+    /// the region towards the far end, negative angles lean them out
+    pub draft: f64,
 }
 
 impl Default for ExtrudeOperation {
@@ -30,82 +106,96 @@ impl Default for ExtrudeOperation {
             capped: true,
             twist: 0.0,
             taper: 1.0,
+            draft: 0.0,
         }
     }
 }
 
 impl ExtrudeOperation {
-    /// Extrude a profile represented as a list of vertices
+    /// Extrude a closed profile represented as a list of vertices
     pub fn extrude_profile(&self, profile: &[Point3]) -> Result<HalfEdgeMesh, TopologyError> {
         if profile.len() < 3 {
             return Err(TopologyError::InsufficientVertices);
         }
+        self.extrude_region(&ProfileRegion::new(profile.to_vec()))
+    }
+
+    /// Extrude a closed region, holes included
+    pub fn extrude_region(&self, region: &ProfileRegion) -> Result<HalfEdgeMesh, TopologyError> {
+        region.validate()?;
+        let length = self.direction.norm();
+        if length < EPSILON {
+            return Err(TopologyError::InvalidDirection);
+        }
+        let axis = self.direction / length;
+        let loops = region.oriented(&axis);
+        let center = Self::compute_centroid(&loops[0]);
+        let inset = length * self.draft.tan();
 
         let mut mesh = HalfEdgeMesh::new();
-        let n = profile.len();
+        let mut bottoms = Vec::with_capacity(loops.len());
+        let mut tops = Vec::with_capacity(loops.len());
+        let mut top_points = Vec::with_capacity(loops.len());
+
+        for ring in &loops {
+            let top = self.far_ring(ring, &axis, center, inset)?;
+            bottoms.push(ring.iter().map(|p| mesh.add_vertex(*p)).collect::<Vec<_>>());
+            tops.push(top.iter().map(|p| mesh.add_vertex(*p)).collect::<Vec<_>>());
+            top_points.push(top);
+        }
 
-        // Create bottom vertices
-        let mut bottom_vertices = Vec::new();
-        for point in profile {
-            bottom_vertices.push(mesh.add_vertex(*point));
+        for (bottom, top) in bottoms.iter().zip(&tops) {
+            add_band(&mut mesh, bottom, top)?;
         }
 
-        // Create top vertices with optional twist and taper
-        let mut top_vertices = Vec::new();
-        let length = self.direction.norm();
+        if self.capped {
+            add_cap(&mut mesh, &bottoms, &loops, &-axis)?;
+            add_cap(&mut mesh, &tops, &top_points, &axis)?;
+        }
+
+        mesh.update_vertex_normals();
+
+        Ok(mesh)
+    }
 
-        for point in profile {
-            let mut transformed = *point;
+    /// A loop moved to the far end with draft, taper and twist applied
+    fn far_ring(
+        &self,
+        ring: &[Point3],
+        axis: &Vector3,
+        center: Point3,
+        inset: f64,
+    ) -> Result<Vec<Point3>, TopologyError> {
+        let mut points = ring.to_vec();
+
+        if inset.abs() > EPSILON {
+            points = offset_ring(ring, axis, inset);
+            // A draft too steep for the profile turns edges back on themselves
+            let n = ring.len();
+            if (0..n).any(|i| {
+                let j = (i + 1) % n;
+                (points[j] - points[i]).dot(&(ring[j] - ring[i])) <= 0.0
+            }) {
+                return Err(TopologyError::InvalidDraft);
+            }
+        }
 
+        let length = self.direction.norm();
+        for point in &mut points {
             // Apply taper (scale from center)
             if (self.taper - 1.0).abs() > EPSILON {
-                let center = Self::compute_centroid(profile);
-                let offset = transformed - center;
-                transformed = center + offset * self.taper;
+                *point = center + (*point - center) * self.taper;
             }
 
             // Apply twist
             if self.twist.abs() > EPSILON {
-                let angle = self.twist * length;
-                let center = Self::compute_centroid(profile);
-                transformed = Self::rotate_around_axis(transformed, center, &self.direction, angle);
+                *point = Self::rotate_around_axis(*point, center, &self.direction, self.twist * length);
             }
 
-            // Translate
-            transformed += self.direction;
-
-            top_vertices.push(mesh.add_vertex(transformed));
-        }
-
-        // Create side faces
-        for i in 0..n {
-            let next_i = (i + 1) % n;
-
-            let v0 = bottom_vertices[i];
-            let v1 = bottom_vertices[next_i];
-            let v2 = top_vertices[next_i];
-            let v3 = top_vertices[i];
-
-            // Create a quad (two triangles)
-            mesh.add_face(&[v0, v1, v2, v3])
-                .map_err(|_| TopologyError::MeshCreationFailed)?;
+            *point += self.direction;
         }
 
-        // Create caps if requested
-        if self.capped {
-            // Bottom cap (reversed winding)
-            let bottom_verts: Vec<_> = bottom_vertices.iter().rev().copied().collect();
-            mesh.add_face(&bottom_verts)
-                .map_err(|_| TopologyError::MeshCreationFailed)?;
-
-            // Top cap
-            mesh.add_face(&top_vertices)
-                .map_err(|_| TopologyError::MeshCreationFailed)?;
-        }
-
-        mesh.update_vertex_normals();
-
-        Ok(mesh)
+        Ok(points)
     }
 
     fn compute_centroid(points: &[Point3]) -> Point3 {
@@ -213,6 +303,85 @@ impl RevolveOperation {
 
         Ok(mesh)
     }
+
+    /// Revolve a closed region into a solid
+    ///
+    /// The region must lie in a plane through the axis, on one side of it.
+    /// Boundary points on the axis are shared by every step rather than
+    /// repeated, and a partial revolution is capped at both ends.
+    pub fn revolve_region(&self, region: &ProfileRegion) -> Result<HalfEdgeMesh, TopologyError> {
+        region.validate()?;
+        if self.segments < 3 {
+            return Err(TopologyError::InvalidSegmentCount);
+        }
+        if self.axis_direction.norm() < EPSILON || self.angle.abs() < EPSILON {
+            return Err(TopologyError::InvalidDirection);
+        }
+
+        let axis = self.axis_direction.normalize();
+        let radial = |p: &Point3| {
+            let d = p - self.axis_origin;
+            d - axis * d.dot(&axis)
+        };
+        let side = radial(&ExtrudeOperation::compute_centroid(&region.outer));
+        if side.norm() < EPSILON {
+            return Err(TopologyError::ProfileCrossesAxis);
+        }
+        let side = side.normalize();
+        if std::iter::once(&region.outer)
+            .chain(&region.holes)
+            .flatten()
+            .any(|p| radial(p).dot(&side) < -EPSILON)
+        {
+            return Err(TopologyError::ProfileCrossesAxis);
+        }
+
+        // Direction the profile moves in as it turns
+        let sweep = axis.cross(&side) * self.angle.signum();
+        let loops = region.oriented(&sweep);
+        let full = self.angle.abs() >= 2.0 * PI - EPSILON;
+        let steps = self.segments;
+        let columns = if full { steps } else { steps + 1 };
+        let rotate = |p: Point3, j: usize| {
+            let angle = self.angle * j as f64 / steps as f64;
+            ExtrudeOperation::rotate_around_axis(p, self.axis_origin, &axis, angle)
+        };
+
+        let mut mesh = HalfEdgeMesh::new();
+        let mut rings: Vec<Vec<Vec<VertexHandle>>> = Vec::with_capacity(loops.len());
+        for ring in &loops {
+            let on_axis: Vec<Option<VertexHandle>> = ring
+                .iter()
+                .map(|p| (radial(p).norm() < EPSILON).then(|| mesh.add_vertex(*p)))
+                .collect();
+            let mut cols = Vec::with_capacity(columns);
+            for j in 0..columns {
+                let mut col = Vec::with_capacity(ring.len());
+                for (p, shared) in ring.iter().zip(&on_axis) {
+                    col.push(shared.unwrap_or_else(|| mesh.add_vertex(rotate(*p, j))));
+                }
+                cols.push(col);
+            }
+            for j in 0..steps {
+                add_band(&mut mesh, &cols[j], &cols[(j + 1) % columns])?;
+            }
+            rings.push(cols);
+        }
+
+        if !full {
+            let first: Vec<_> = rings.iter().map(|cols| cols[0].clone()).collect();
+            let last: Vec<_> = rings.iter().map(|cols| cols[steps].clone()).collect();
+            let end_points: Vec<Vec<Point3>> =
+                loops.iter().map(|ring| ring.iter().map(|p| rotate(*p, steps)).collect()).collect();
+            let end_normal = rotate(self.axis_origin + sweep, steps) - self.axis_origin;
+            add_cap(&mut mesh, &first, &loops, &-sweep)?;
+            add_cap(&mut mesh, &last, &end_points, &end_normal)?;
+        }
+
+        mesh.update_vertex_normals();
+
+        Ok(mesh)
+    }
 }
 
 /// Sweep a profile along a path
@@ -221,80 +390,96 @@ pub struct SweepOperation {
     pub path: NurbsCurve,
     /// Number of samples along the path
     pub samples: usize,
-    /// Whether to keep the profile perpendicular to the path
+    /// Whether to turn the profile with the path, using rotation-minimizing
+    /// frames; when false the profile keeps its starting orientation
     pub frenet_frame: bool,
+    /// Total twist in radians about the path, spread evenly along its length
+    pub twist: f64,
+    /// Whether to cap the ends
+    pub capped: bool,
 }
 
 impl SweepOperation {
-    /// Sweep a profile along the path curve
+    /// Capped sweep along a path, turning with it, without twist
+    pub fn new(path: NurbsCurve) -> Self {
+        Self {
+            path,
+            samples: 32,
+            frenet_frame: true,
+            twist: 0.0,
+            capped: true,
+        }
+    }
+
+    /// Sweep a closed profile along the path curve
+    ///
+    /// Profile coordinates are local to the start of the path: x and y
+    /// across it, z along it.
     pub fn sweep_profile(&self, profile: &[Point3]) -> Result<HalfEdgeMesh, TopologyError> {
         if profile.len() < 3 {
             return Err(TopologyError::InsufficientVertices);
         }
+        self.sweep_region(&ProfileRegion::new(profile.to_vec()))
+    }
 
+    /// Sweep a closed region, holes included, along the path curve
+    pub fn sweep_region(&self, region: &ProfileRegion) -> Result<HalfEdgeMesh, TopologyError> {
+        region.validate()?;
         if self.samples < 2 {
             return Err(TopologyError::InvalidSegmentCount);
         }
 
-        let mut mesh = HalfEdgeMesh::new();
-        let n = profile.len();
-        let m = self.samples;
-
         // Sample the path
         let path_points = self.path
-            .tessellate(m)
+            .tessellate(self.samples)
             .map_err(|_| TopologyError::PathEvaluationFailed)?;
+        let frames = self.frames(&path_points)?;
 
-        if path_points.len() < 2 {
-            return Err(TopologyError::PathEvaluationFailed);
+        // Twist follows arc length, so uneven sampling doesn't bunch it up
+        let mut travelled = vec![0.0; path_points.len()];
+        for i in 1..path_points.len() {
+            travelled[i] = travelled[i - 1] + (path_points[i] - path_points[i - 1]).norm();
         }
+        let total = travelled[travelled.len() - 1];
 
-        // Create vertex grid
-        let mut vertex_grid = vec![vec![VertexHandle(0); m]; n];
-
-        for i in 0..m {
-            let t = i as f64 / (m - 1) as f64;
-
-            // Get path point and tangent
-            let path_point = path_points[i];
-            let tangent = if i < m - 1 {
-                (path_points[i + 1] - path_points[i]).normalize()
-            } else {
-                (path_points[i] - path_points[i - 1]).normalize()
-            };
-
-            // Compute frame at this point
-            let (up, right) = if self.frenet_frame {
-                Self::compute_frenet_frame(&tangent)
-            } else {
-                Self::compute_simple_frame(&tangent)
-            };
-
-            // Transform profile to this frame
-            for j in 0..n {
-                let local_point = profile[j];
-                let transformed = path_point
-                    + right * local_point.x
-                    + up * local_point.y
-                    + tangent * local_point.z;
-
-                vertex_grid[j][i] = mesh.add_vertex(transformed);
+        let loops = region.oriented(&Vector3::new(0.0, 0.0, 1.0));
+        let mut mesh = HalfEdgeMesh::new();
+        let mut rings: Vec<Vec<Vec<VertexHandle>>> = Vec::with_capacity(loops.len());
+        let mut end_points: Vec<[Vec<Point3>; 2]> = Vec::with_capacity(loops.len());
+
+        for ring in &loops {
+            let mut cols = Vec::with_capacity(frames.len());
+            let mut ends: [Vec<Point3>; 2] = Default::default();
+            for (i, frame) in frames.iter().enumerate() {
+                let (sin, cos) = (self.twist * travelled[i] / total).sin_cos();
+                let points: Vec<Point3> = ring
+                    .iter()
+                    .map(|p| {
+                        let (x, y) = (p.x * cos - p.y * sin, p.x * sin + p.y * cos);
+                        frame.origin + frame.x * x + frame.y * y + frame.tangent * p.z
+                    })
+                    .collect();
+                cols.push(points.iter().map(|p| mesh.add_vertex(*p)).collect::<Vec<_>>());
+                if i == 0 {
+                    ends[0] = points;
+                } else if i == frames.len() - 1 {
+                    ends[1] = points;
+                }
+            }
+            for pair in cols.windows(2) {
+                add_band(&mut mesh, &pair[0], &pair[1])?;
             }
+            rings.push(cols);
+            end_points.push(ends);
         }
 
-        // Create faces
-        for i in 0..n {
-            for j in 0..m - 1 {
-                let next_i = (i + 1) % n;
-
-                let v0 = vertex_grid[i][j];
-                let v1 = vertex_grid[next_i][j];
-                let v2 = vertex_grid[next_i][j + 1];
-                let v3 = vertex_grid[i][j + 1];
-
-                mesh.add_face(&[v0, v1, v2, v3])
-                    .map_err(|_| TopologyError::MeshCreationFailed)?;
-            }
+        if self.capped {
+            let first: Vec<_> = rings.iter().map(|cols| cols[0].clone()).collect();
+            let last: Vec<_> = rings.iter().map(|cols| cols[cols.len() - 1].clone()).collect();
+            let start: Vec<_> = end_points.iter().map(|e| e[0].clone()).collect();
+            let end: Vec<_> = end_points.iter().map(|e| e[1].clone()).collect();
+            add_cap(&mut mesh, &first, &start, &-frames[0].tangent)?;
+            add_cap(&mut mesh, &last, &end, &frames[frames.len() - 1].tangent)?;
         }
 
         mesh.update_vertex_normals();
@@ -302,23 +487,79 @@ impl SweepOperation {
         Ok(mesh)
     }
 
-    fn compute_frenet_frame(tangent: &Vector3) -> (Vector3, Vector3) {
-        // Simplified Frenet frame (constant normal)
-        let arbitrary = if tangent.x.abs() < 0.9 {
+    /// Frames along the sampled path
+    ///
+    /// Rotation-minimizing frames by double reflection (Wang et al. 2008),
+    /// which don't flip at inflections the way Frenet frames do.
+    fn frames(&self, path: &[Point3]) -> Result<Vec<Frame>, TopologyError> {
+        if path.len() < 2 {
+            return Err(TopologyError::PathEvaluationFailed);
+        }
+        let last = path.len() - 1;
+        let mut tangents = Vec::with_capacity(path.len());
+        for i in 0..path.len() {
+            let chord = path[(i + 1).min(last)] - path[i.saturating_sub(1)];
+            if chord.norm() < EPSILON {
+                return Err(TopologyError::PathEvaluationFailed);
+            }
+            tangents.push(chord.normalize());
+        }
+
+        let t0 = tangents[0];
+        let arbitrary = if t0.x.abs() < 0.9 {
             Vector3::new(1.0, 0.0, 0.0)
         } else {
             Vector3::new(0.0, 1.0, 0.0)
         };
+        let mut x = (arbitrary - t0 * arbitrary.dot(&t0)).normalize();
+        let mut frames = Vec::with_capacity(path.len());
+
+        for (i, origin) in path.iter().enumerate() {
+            let tangent = if self.frenet_frame { tangents[i] } else { t0 };
+            if self.frenet_frame && i > 0 {
+                let v1 = path[i] - path[i - 1];
+                let c1 = v1.norm_squared();
+                if c1 > EPSILON * EPSILON {
+                    let reflected_x = x - v1 * (2.0 / c1 * v1.dot(&x));
+                    let reflected_t = tangents[i - 1] - v1 * (2.0 / c1 * v1.dot(&tangents[i - 1]));
+                    let v2 = tangent - reflected_t;
+                    let c2 = v2.norm_squared();
+                    x = if c2 > EPSILON * EPSILON {
+                        reflected_x - v2 * (2.0 / c2 * v2.dot(&reflected_x))
+                    } else {
+                        reflected_x
+                    };
+                }
+                x = (x - tangent * x.dot(&tangent)).normalize();
+            }
+            frames.push(Frame {
+                origin: *origin,
+                tangent,
+                x,
+                y: tangent.cross(&x),
+            });
+        }
 
-        let right = tangent.cross(&arbitrary).normalize();
-        let up = right.cross(tangent).normalize();
-
-        (up, right)
+        Ok(frames)
     }
+}
 
-    fn compute_simple_frame(tangent: &Vector3) -> (Vector3, Vector3) {
-        Self::compute_frenet_frame(tangent)
-    }
+/// Local frame on a sweep path; `x` and `y` span the profile plane
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    origin: Point3,
+    tangent: Vector3,
+    x: Vector3,
+    y: Vector3,
+}
+
+/// Guide curve steering a loft between its profiles
+#[derive(Debug, Clone)]
+pub struct LoftGuide {
+    /// Profile vertex the guide runs through, the same index in every profile
+    pub vertex: usize,
+    /// Guide curve, running from the first profile to the last
+    pub curve: NurbsCurve,
 }
 
 /// Loft between multiple profiles
@@ -327,10 +568,33 @@ pub struct LoftOperation {
     pub profiles: Vec<Vec<Point3>>,
     /// Whether to close the loft (connect first and last profiles)
     pub closed: bool,
+    /// Guide curves the sections between profiles follow
+    pub guides: Vec<LoftGuide>,
+    /// Sections generated between consecutive profiles when guided
+    pub sections: usize,
+    /// Whether to cap the first and last profiles of an open loft
+    pub capped: bool,
+}
+
+impl Default for LoftOperation {
+    fn default() -> Self {
+        Self {
+            profiles: Vec::new(),
+            closed: false,
+            guides: Vec::new(),
+            sections: 8,
+            capped: true,
+        }
+    }
 }
 
 impl LoftOperation {
     /// Create a lofted surface between profiles
+    ///
+    /// Without guides consecutive profiles are joined directly. Each guide
+    /// bends the sections between profiles: the vertex it runs through
+    /// follows the guide, and the vertices between two guides blend their
+    /// pull by position around the profile.
     pub fn loft(&self) -> Result<HalfEdgeMesh, TopologyError> {
         if self.profiles.len() < 2 {
             return Err(TopologyError::InsufficientProfiles);
@@ -348,48 +612,287 @@ impl LoftOperation {
             return Err(TopologyError::InsufficientVertices);
         }
 
-        let mut mesh = HalfEdgeMesh::new();
         let m = self.profiles.len();
 
-        // Create vertex grid
-        let mut vertex_grid = vec![vec![VertexHandle(0); m]; n];
-
-        for i in 0..n {
-            for j in 0..m {
-                vertex_grid[i][j] = mesh.add_vertex(self.profiles[j][i]);
+        // Wind the profiles counter-clockwise around the loft direction so
+        // an open loft faces outward
+        let direction = ExtrudeOperation::compute_centroid(&self.profiles[m - 1])
+            - ExtrudeOperation::compute_centroid(&self.profiles[0]);
+        let reversed = !self.closed && newell_normal(&self.profiles[0]).dot(&direction) < 0.0;
+        let profiles: Vec<Vec<Point3>> = self
+            .profiles
+            .iter()
+            .map(|p| if reversed { p.iter().rev().copied().collect() } else { p.clone() })
+            .collect();
+
+        let guides = self
+            .guides
+            .iter()
+            .map(|guide| {
+                if guide.vertex >= n {
+                    return Err(TopologyError::InvalidGuide);
+                }
+                let vertex = if reversed { n - 1 - guide.vertex } else { guide.vertex };
+                GuidePath::new(guide, vertex, &profiles)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Sections, profiles included, in order along the loft
+        let mut rings: Vec<Vec<Point3>> = Vec::new();
+        for j in 0..m - 1 {
+            rings.push(profiles[j].clone());
+            if guides.is_empty() {
+                continue;
+            }
+            for k in 1..=self.sections {
+                let f = k as f64 / (self.sections + 1) as f64;
+                rings.push(guided_section(&profiles[j], &profiles[j + 1], &guides, j, f, n));
             }
         }
+        rings.push(profiles[m - 1].clone());
 
-        // Create faces between profiles
-        let profile_count = if self.closed { m } else { m - 1 };
+        let mut mesh = HalfEdgeMesh::new();
+        let handles: Vec<Vec<VertexHandle>> = rings
+            .iter()
+            .map(|ring| ring.iter().map(|p| mesh.add_vertex(*p)).collect())
+            .collect();
+
+        // Create faces between sections
+        for pair in handles.windows(2) {
+            add_band(&mut mesh, &pair[0], &pair[1])?;
+        }
+        if self.closed {
+            add_band(&mut mesh, &handles[handles.len() - 1], &handles[0])?;
+        } else if self.capped {
+            add_cap(&mut mesh, &handles[..1], &rings[..1], &-direction)?;
+            add_cap(&mut mesh, &handles[handles.len() - 1..], &rings[rings.len() - 1..], &direction)?;
+        }
 
-        for i in 0..n {
-            for j in 0..profile_count {
-                let next_i = (i + 1) % n;
-                let next_j = (j + 1) % m;
+        mesh.update_vertex_normals();
 
-                let v0 = vertex_grid[i][j];
-                let v1 = vertex_grid[next_i][j];
-                let v2 = vertex_grid[next_i][next_j];
-                let v3 = vertex_grid[i][next_j];
+        Ok(mesh)
+    }
+}
 
-                mesh.add_face(&[v0, v1, v2, v3])
-                    .map_err(|_| TopologyError::MeshCreationFailed)?;
+/// A loft guide sampled as a polyline, with where each profile sits on it
+struct GuidePath {
+    vertex: usize,
+    points: Vec<Point3>,
+    /// Arc length at each polyline point
+    lengths: Vec<f64>,
+    /// Arc length at each profile
+    stations: Vec<f64>,
+}
+
+impl GuidePath {
+    fn new(guide: &LoftGuide, vertex: usize, profiles: &[Vec<Point3>]) -> Result<Self, TopologyError> {
+        let points = guide
+            .curve
+            .tessellate((32 * profiles.len()).max(64))
+            .map_err(|_| TopologyError::PathEvaluationFailed)?;
+        let mut lengths = vec![0.0; points.len()];
+        for i in 1..points.len() {
+            lengths[i] = lengths[i - 1] + (points[i] - points[i - 1]).norm();
+        }
+
+        let mut path = Self {
+            vertex,
+            points,
+            lengths,
+            stations: Vec::with_capacity(profiles.len()),
+        };
+        for profile in profiles {
+            path.stations.push(path.closest(&profile[vertex]));
+        }
+        if path.stations.windows(2).any(|w| w[1] <= w[0]) {
+            return Err(TopologyError::InvalidGuide);
+        }
+        Ok(path)
+    }
+
+    /// Arc length of the closest point on the guide
+    fn closest(&self, target: &Point3) -> f64 {
+        let mut best = (f64::INFINITY, 0.0);
+        for i in 1..self.points.len() {
+            let (a, b) = (self.points[i - 1], self.points[i]);
+            let ab = b - a;
+            let t = if ab.norm_squared() > 0.0 {
+                ((target - a).dot(&ab) / ab.norm_squared()).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let distance = (a + ab * t - target).norm();
+            if distance < best.0 {
+                best = (distance, self.lengths[i - 1] + t * ab.norm());
             }
         }
+        best.1
+    }
 
-        mesh.update_vertex_normals();
+    fn at(&self, length: f64) -> Point3 {
+        let i = self.lengths.partition_point(|&l| l < length).clamp(1, self.points.len() - 1);
+        let span = self.lengths[i] - self.lengths[i - 1];
+        let t = if span > 0.0 { (length - self.lengths[i - 1]) / span } else { 0.0 };
+        self.points[i - 1] + (self.points[i] - self.points[i - 1]) * t
+    }
 
-        Ok(mesh)
+    /// How far the guide bows away from its chord at fraction `f` of span `j`
+    fn deviation(&self, j: usize, f: f64) -> Vector3 {
+        let (s0, s1) = (self.stations[j], self.stations[j + 1]);
+        let (a, b) = (self.at(s0), self.at(s1));
+        self.at(s0 + (s1 - s0) * f) - (a + (b - a) * f)
     }
 }
 
-/// Shell operation (offset surfaces)
+/// Section at fraction `f` between two profiles, bent by the guides
+fn guided_section(
+    from: &[Point3],
+    to: &[Point3],
+    guides: &[GuidePath],
+    span: usize,
+    f: f64,
+    n: usize,
+) -> Vec<Point3> {
+    let mut pulls: Vec<(usize, Vector3)> = guides.iter().map(|g| (g.vertex, g.deviation(span, f))).collect();
+    pulls.sort_by_key(|&(vertex, _)| vertex);
+
+    (0..n)
+        .map(|i| {
+            let base = from[i] + (to[i] - from[i]) * f;
+            // Guides on either side of this vertex, going around the profile
+            let after = pulls.partition_point(|&(vertex, _)| vertex <= i);
+            let (before_vertex, before_pull) = pulls[(after + pulls.len() - 1) % pulls.len()];
+            let (after_vertex, after_pull) = pulls[after % pulls.len()];
+            let gap = (after_vertex + n - before_vertex) % n;
+            let pull = if gap == 0 {
+                before_pull
+            } else {
+                let t = ((i + n - before_vertex) % n) as f64 / gap as f64;
+                before_pull * (1.0 - t) + after_pull * t
+            };
+            base + pull
+        })
+        .collect()
+}
+
+/// Unnormalized loop normal by Newell's method
+pub(super) fn newell_normal(points: &[Point3]) -> Vector3 {
+    let mut normal = Vector3::zeros();
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        normal.x += (a.y - b.y) * (a.z + b.z);
+        normal.y += (a.z - b.z) * (a.x + b.x);
+        normal.z += (a.x - b.x) * (a.y + b.y);
+    }
+    normal
+}
+
+/// A loop without a repeated closing point
+fn open_ring(points: &[Point3]) -> &[Point3] {
+    match points {
+        [first, .., last] if points.len() > 1 && (first - last).norm() < EPSILON => &points[..points.len() - 1],
+        _ => points,
+    }
+}
+
+/// A loop wound counter-clockwise (or clockwise) around `axis`
+pub(super) fn wound(points: &[Point3], axis: &Vector3, counter_clockwise: bool) -> Vec<Point3> {
+    let mut ring = open_ring(points).to_vec();
+    if (newell_normal(&ring).dot(axis) > 0.0) != counter_clockwise {
+        ring.reverse();
+    }
+    ring
+}
+
+/// Loop offset by `distance` to the left of travel, in the plane normal to
+/// `axis`, with mitered corners
+fn offset_ring(ring: &[Point3], axis: &Vector3, distance: f64) -> Vec<Point3> {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let (prev, cur, next) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+            let left_in = axis.cross(&(cur - prev)).normalize();
+            let left_out = axis.cross(&(next - cur)).normalize();
+            let denom = 1.0 + left_in.dot(&left_out);
+            let miter = if denom > EPSILON { (left_in + left_out) / denom } else { left_in };
+            cur + miter * distance
+        })
+        .collect()
+}
+
+/// Side faces joining two rings of vertices
+///
+/// Vertices shared by both rings (on an axis of revolution) turn quads into
+/// triangles, and edges shared entirely get no face.
+fn add_band(mesh: &mut HalfEdgeMesh, from: &[VertexHandle], to: &[VertexHandle]) -> Result<(), TopologyError> {
+    let n = from.len();
+    for i in 0..n {
+        let next = (i + 1) % n;
+        let mut face = vec![from[i], from[next], to[next], to[i]];
+        face.dedup();
+        if face.len() > 1 && face.first() == face.last() {
+            face.pop();
+        }
+        if face.len() >= 3 {
+            mesh.add_face(&face).map_err(|_| TopologyError::MeshCreationFailed)?;
+        }
+    }
+    Ok(())
+}
+
+/// Cap a region facing `normal`; regions with holes are triangulated
+fn add_cap(
+    mesh: &mut HalfEdgeMesh,
+    loops: &[Vec<VertexHandle>],
+    points: &[Vec<Point3>],
+    normal: &Vector3,
+) -> Result<(), TopologyError> {
+    if loops.len() == 1 {
+        let mut face = loops[0].clone();
+        if newell_normal(&points[0]).dot(normal) < 0.0 {
+            face.reverse();
+        }
+        mesh.add_face(&face).map_err(|_| TopologyError::MeshCreationFailed)?;
+        return Ok(());
+    }
+
+    // Counter-clockwise in (u, v) faces along the normal
+    let w = normal.normalize();
+    let seed = if w.x.abs() < 0.9 { Vector3::new(1.0, 0.0, 0.0) } else { Vector3::new(0.0, 1.0, 0.0) };
+    let u = (seed - w * seed.dot(&w)).normalize();
+    let v = w.cross(&u);
+    let flat = |ring: &Vec<Point3>| ring.iter().map(|p| Point2D::new(p.coords.dot(&u), p.coords.dot(&v))).collect();
+    let holes: Vec<Vec<Point2D>> = points[1..].iter().map(flat).collect();
+    let fill = tessellate(&flat(&points[0]), &holes);
+
+    let handles: Vec<VertexHandle> = loops.concat();
+    for triangle in fill.indices.chunks_exact(3) {
+        let face = [handles[triangle[0] as usize], handles[triangle[1] as usize], handles[triangle[2] as usize]];
+        mesh.add_face(&face).map_err(|_| TopologyError::MeshCreationFailed)?;
+    }
+    Ok(())
+}
+
+/// Dihedral angle below which an edge is a facet of a curved surface
+/// rather than a crease
+pub(super) const SMOOTH_EDGE_ANGLE: f64 = PI / 6.0;
+
+/// Shell operation
+///
+/// [`shell_mesh`](Self::shell_mesh) offsets a surface;
+/// [`hollow`](Self::hollow) turns a closed solid into a thin-walled one,
+/// with `open_faces` removed so the cavity opens through them.
+#[derive(Debug, Clone, Default)]
 pub struct ShellOperation {
     /// Thickness of the shell
     pub thickness: f64,
     /// Whether to create inward or outward offset
     pub outward: bool,
+    /// Faces removed to open the hollow; empty for a closed cavity
+    pub open_faces: Vec<FaceHandle>,
+    /// Smallest radius allowed where a curved wall is offset, so thin
+    /// walls don't end in knife edges
+    pub min_radius: f64,
 }
 
 impl ShellOperation {
@@ -425,6 +928,296 @@ impl ShellOperation {
 
         Ok(result)
     }
+
+    /// Open the shell through `faces` as well
+    pub fn with_open_faces(mut self, faces: impl IntoIterator<Item = FaceHandle>) -> Self {
+        self.open_faces.extend(faces);
+        self
+    }
+
+    /// Faces of a closed solid that can't be shelled, without building it
+    pub fn check(&self, mesh: &HalfEdgeMesh) -> Result<Vec<ShellFailure>, TopologyError> {
+        let solid = ShellSolid::new(mesh, &self.open_faces)?;
+        if self.thickness <= EPSILON {
+            return Err(TopologyError::InvalidThickness);
+        }
+        let offsets = self.offsets(&solid)?;
+        Ok(self.failures(&solid, &offsets))
+    }
+
+    /// Hollow a closed solid to the wall thickness
+    ///
+    /// The offset wall runs parallel to every face at the thickness. Open
+    /// faces are left out and their edges joined by rim faces, with the
+    /// inner wall meeting the open face's plane. Fails with
+    /// [`TopologyError::ShellFailed`] listing every face whose wall would
+    /// collapse or break the minimum radius.
+    pub fn hollow(&self, mesh: &HalfEdgeMesh) -> Result<HalfEdgeMesh, TopologyError> {
+        let solid = ShellSolid::new(mesh, &self.open_faces)?;
+        if self.thickness <= EPSILON {
+            return Err(TopologyError::InvalidThickness);
+        }
+        let offsets = self.offsets(&solid)?;
+        let failures = self.failures(&solid, &offsets);
+        if !failures.is_empty() {
+            return Err(TopologyError::ShellFailed(failures));
+        }
+
+        // The offset surface is the inner wall inward and the outer wall outward
+        let mut result = HalfEdgeMesh::new();
+        let mut outer = HashMap::new();
+        let mut inner = HashMap::new();
+        for (&vh, delta) in &offsets {
+            let position = solid.positions[&vh];
+            let moved = Point3::from(position.coords + delta);
+            let (o, i) = if self.outward { (moved, position) } else { (position, moved) };
+            outer.insert(vh, result.add_vertex(o));
+            inner.insert(vh, result.add_vertex(i));
+        }
+
+        let add = |mesh: &mut HalfEdgeMesh, face: &[VertexHandle]| {
+            mesh.add_face(face).map(|_| ()).map_err(|_| TopologyError::MeshCreationFailed)
+        };
+        for (_, vertices) in solid.kept() {
+            let walls: Vec<VertexHandle> = vertices.iter().map(|v| outer[v]).collect();
+            add(&mut result, &walls)?;
+            let walls: Vec<VertexHandle> = vertices.iter().rev().map(|v| inner[v]).collect();
+            add(&mut result, &walls)?;
+
+            for (u, v) in ring_edges(vertices) {
+                if solid.open.contains(&solid.edges[&(v, u)]) {
+                    add(&mut result, &[outer[&v], outer[&u], inner[&u], inner[&v]])?;
+                }
+            }
+        }
+
+        result.update_vertex_normals();
+        Ok(result)
+    }
+
+    /// Offset of each vertex of a kept face
+    ///
+    /// Least-squares solution of the vertex's face planes, kept faces moved
+    /// by the thickness and open faces staying put, so planar walls come out
+    /// at exactly the thickness.
+    fn offsets(&self, solid: &ShellSolid) -> Result<HashMap<VertexHandle, Vector3>, TopologyError> {
+        let distance = if self.outward { self.thickness } else { -self.thickness };
+        let mut planes: HashMap<VertexHandle, Vec<(Vector3, f64)>> = HashMap::new();
+        for (face, vertices) in &solid.faces {
+            let target = if solid.open.contains(face) { 0.0 } else { distance };
+            let normal = solid.normals[face];
+            for v in vertices {
+                let planes = planes.entry(*v).or_default();
+                // Faces of one plane count once; a kept face wins over an open one
+                match planes.iter_mut().find(|(n, _)| n.dot(&normal) > 1.0 - 1e-9) {
+                    Some(plane) if target != 0.0 => plane.1 = target,
+                    Some(_) => {}
+                    None => planes.push((normal, target)),
+                }
+            }
+        }
+
+        let used: HashSet<VertexHandle> =
+            solid.kept().flat_map(|(_, vertices)| vertices.iter().copied()).collect();
+        let mut offsets = HashMap::new();
+        for v in used {
+            let mut a = nalgebra::Matrix3::zeros();
+            let mut b = Vector3::zeros();
+            for (normal, target) in &planes[&v] {
+                a += normal * normal.transpose();
+                b += normal * *target;
+            }
+            let delta = a.svd(true, true).solve(&b, 1e-9).map_err(|_| TopologyError::InvalidMesh)?;
+            offsets.insert(v, delta);
+        }
+        Ok(offsets)
+    }
+
+    fn failures(&self, solid: &ShellSolid, offsets: &HashMap<VertexHandle, Vector3>) -> Vec<ShellFailure> {
+        let mut failures = Vec::new();
+        for (face, vertices) in solid.kept() {
+            let moved: Vec<Point3> = vertices
+                .iter()
+                .map(|v| Point3::from(solid.positions[v].coords + offsets[v]))
+                .collect();
+            // Inside out, or shrunk past a point so its edges run backwards
+            let original = solid.areas[&face];
+            let offset = newell_normal(&moved);
+            let n = vertices.len();
+            let reversed = (0..n).any(|i| {
+                let edge = solid.positions[&vertices[(i + 1) % n]] - solid.positions[&vertices[i]];
+                (moved[(i + 1) % n] - moved[i]).dot(&edge) <= 0.0
+            });
+            if reversed || offset.dot(&solid.normals[&face]) <= original * 1e-6 {
+                failures.push(ShellFailure { face, reason: ShellFailureReason::Collapsed });
+                continue;
+            }
+
+            // Curvature across smooth edges that bend toward the offset side
+            let centroid = solid.centroid(vertices);
+            let radius = ring_edges(vertices)
+                .filter_map(|(u, v)| {
+                    let neighbour = solid.edges[&(v, u)];
+                    let angle = solid.normals[&face].angle(&solid.normals[&neighbour]);
+                    let towards = solid.centroid(&solid.faces[&neighbour]) - centroid;
+                    let convex = towards.dot(&solid.normals[&face]) < 0.0;
+                    (angle > 1e-6 && angle < SMOOTH_EDGE_ANGLE && convex != self.outward)
+                        .then(|| towards.norm() / (2.0 * (angle / 2.0).sin()))
+                })
+                .min_by(f64::total_cmp);
+            if let Some(radius) = radius {
+                let offset_radius = radius - self.thickness;
+                if offset_radius < self.min_radius {
+                    failures.push(ShellFailure {
+                        face,
+                        reason: ShellFailureReason::RadiusTooSmall { radius, offset_radius },
+                    });
+                }
+            }
+        }
+        failures.sort_by_key(|f| f.face.0);
+        failures
+    }
+}
+
+/// Faces of a closed solid, as the shell and edge blends see them
+pub(super) struct ShellSolid {
+    pub(super) faces: HashMap<FaceHandle, Vec<VertexHandle>>,
+    pub(super) positions: HashMap<VertexHandle, Point3>,
+    /// Unit outward normal of each face
+    pub(super) normals: HashMap<FaceHandle, Vector3>,
+    /// Twice the area of each face
+    areas: HashMap<FaceHandle, f64>,
+    /// Face to the left of each directed edge
+    pub(super) edges: HashMap<(VertexHandle, VertexHandle), FaceHandle>,
+    open: HashSet<FaceHandle>,
+}
+
+impl ShellSolid {
+    pub(super) fn new(mesh: &HalfEdgeMesh, open_faces: &[FaceHandle]) -> Result<Self, TopologyError> {
+        let mut solid = ShellSolid {
+            faces: HashMap::new(),
+            positions: HashMap::new(),
+            normals: HashMap::new(),
+            areas: HashMap::new(),
+            edges: HashMap::new(),
+            open: open_faces.iter().copied().collect(),
+        };
+        for face in mesh.face_handles() {
+            let vertices = mesh.face_vertices(face).map_err(|_| TopologyError::InvalidMesh)?;
+            let mut points = Vec::with_capacity(vertices.len());
+            for &v in &vertices {
+                let position = mesh.get_vertex(v).map_err(|_| TopologyError::InvalidMesh)?.position;
+                solid.positions.insert(v, position);
+                points.push(position);
+            }
+            let normal = newell_normal(&points);
+            let area = normal.norm();
+            if area < EPSILON * EPSILON {
+                return Err(TopologyError::InvalidMesh);
+            }
+            for (u, v) in ring_edges(&vertices) {
+                if solid.edges.insert((u, v), face).is_some() {
+                    return Err(TopologyError::NotClosed);
+                }
+            }
+            solid.normals.insert(face, normal / area);
+            solid.areas.insert(face, area);
+            solid.faces.insert(face, vertices);
+        }
+
+        if let Some(face) = solid.open.iter().find(|f| !solid.faces.contains_key(f)) {
+            return Err(TopologyError::UnknownFace(face.0));
+        }
+        if solid.edges.keys().any(|(u, v)| !solid.edges.contains_key(&(*v, *u))) {
+            return Err(TopologyError::NotClosed);
+        }
+        if solid.kept().next().is_none() {
+            return Err(TopologyError::NothingToShell);
+        }
+        Ok(solid)
+    }
+
+    /// Faces that stay, in handle order
+    fn kept(&self) -> impl Iterator<Item = (FaceHandle, &Vec<VertexHandle>)> {
+        let mut faces: Vec<_> = self.faces.iter().filter(|(f, _)| !self.open.contains(f)).collect();
+        faces.sort_by_key(|(f, _)| f.0);
+        faces.into_iter().map(|(f, vertices)| (*f, vertices))
+    }
+
+    fn centroid(&self, vertices: &[VertexHandle]) -> Point3 {
+        let sum = vertices.iter().fold(Vector3::zeros(), |acc, v| acc + self.positions[v].coords);
+        Point3::from(sum / vertices.len() as f64)
+    }
+}
+
+/// Consecutive vertex pairs around a face
+pub(super) fn ring_edges(
+    vertices: &[VertexHandle],
+) -> impl Iterator<Item = (VertexHandle, VertexHandle)> + '_ {
+    vertices.iter().enumerate().map(|(i, &u)| (u, vertices[(i + 1) % vertices.len()]))
+}
+
+/// Faces of `mesh` whose outward normal points along `direction`
+///
+/// Selects open faces by direction, which survives rebuilding the solid
+/// where face handles don't.
+pub fn faces_facing(mesh: &HalfEdgeMesh, direction: &Vector3) -> Vec<FaceHandle> {
+    let Some(direction) = direction.try_normalize(EPSILON) else {
+        return Vec::new();
+    };
+    mesh.face_handles()
+        .into_iter()
+        .filter(|&face| {
+            let points: Option<Vec<Point3>> = mesh.face_vertices(face).ok().and_then(|vertices| {
+                vertices.iter().map(|&v| mesh.get_vertex(v).ok().map(|v| v.position)).collect()
+            });
+            points
+                .and_then(|points| newell_normal(&points).try_normalize(EPSILON))
+                .is_some_and(|normal| normal.dot(&direction) > 1.0 - 1e-6)
+        })
+        .collect()
+}
+
+/// Why a face can't be shelled
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShellFailureReason {
+    /// The offset face turns inside out: the wall is thicker than the face
+    /// is wide
+    Collapsed,
+    /// The face is curved too tightly for the thickness and minimum radius
+    RadiusTooSmall {
+        /// Curvature radius of the face
+        radius: f64,
+        /// Radius left after offsetting
+        offset_radius: f64,
+    },
+}
+
+/// A face that can't be shelled
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShellFailure {
+    /// The face
+    pub face: FaceHandle,
+    /// What goes wrong
+    pub reason: ShellFailureReason,
+}
+
+impl fmt::Display for ShellFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reason {
+            ShellFailureReason::Collapsed => write!(f, "face {} collapses", self.face.0),
+            ShellFailureReason::RadiusTooSmall { radius, offset_radius } => write!(
+                f,
+                "face {} radius {:.3} leaves {:.3} after offsetting",
+                self.face.0, radius, offset_radius
+            ),
+        }
+    }
+}
+
+fn describe_failures(failures: &[ShellFailure]) -> String {
+    failures.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
 }
 
 /// Topology operation errors
@@ -450,11 +1243,70 @@ pub enum TopologyError {
 
     #[error("Invalid mesh")]
     InvalidMesh,
+
+    #[error("Extrusion or revolution direction is zero")]
+    InvalidDirection,
+
+    #[error("Draft angle turns the profile inside out")]
+    InvalidDraft,
+
+    #[error("Profile crosses the axis of revolution")]
+    ProfileCrossesAxis,
+
+    #[error("Guide curve does not run through the profiles in order")]
+    InvalidGuide,
+
+    #[error("Shell thickness must be positive")]
+    InvalidThickness,
+
+    #[error("Operation needs a closed solid")]
+    NotClosed,
+
+    #[error("Face {0} is not part of the solid")]
+    UnknownFace(usize),
+
+    #[error("Every face of the solid is open")]
+    NothingToShell,
+
+    #[error("Shell fails: {}", describe_failures(.0))]
+    ShellFailed(Vec<ShellFailure>),
+
+    #[error("Edge {0} is not part of the solid")]
+    UnknownEdge(usize),
+
+    #[error("Fillet radii and chamfer distances must be positive, and chamfer angles less than the edge angle")]
+    InvalidBlendSize,
+
+    #[error("Edge {0} joins coplanar faces")]
+    FlatEdge(usize),
+
+    #[error("Blend on edge {0} is too large for the faces around it")]
+    BlendTooLarge(usize),
+
+    #[error("Edges do not form a chain")]
+    BrokenChain,
 }
 
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::engine3d::boolean::MeshValidity;
+
+    fn square(half: f64) -> Vec<Point3> {
+        vec![
+            Point3::new(-half, -half, 0.0),
+            Point3::new(half, -half, 0.0),
+            Point3::new(half, half, 0.0),
+            Point3::new(-half, half, 0.0),
+        ]
+    }
+
+    fn closed_volume(mesh: &HalfEdgeMesh) -> f64 {
+        let validity = MeshValidity::check(mesh).unwrap();
+        assert!(validity.is_valid(), "{:?}", validity);
+        validity.volume
+    }
 
     #[test]
     fn test_extrude_square() {
@@ -516,10 +1368,224 @@ mod tests {
 
         let loft = LoftOperation {
             profiles,
-            closed: false,
+            ..Default::default()
         };
 
         let mesh = loft.loft();
         assert!(mesh.is_ok());
     }
+
+    #[test]
+    fn test_extrude_region_with_hole() {
+        let region = ProfileRegion::new(square(2.0)).with_hole(square(1.0));
+        let mesh = ExtrudeOperation::default().extrude_region(&region).unwrap();
+        assert!((closed_volume(&mesh) - 12.0).abs() < 1e-9);
+
+        // Clockwise outer loops are rewound
+        let mut reversed = square(2.0);
+        reversed.reverse();
+        let mesh = ExtrudeOperation::default().extrude_profile(&reversed).unwrap();
+        assert!((closed_volume(&mesh) - 16.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_extrude_draft() {
+        let extrude = ExtrudeOperation {
+            draft: 0.25f64.atan(),
+            ..Default::default()
+        };
+        let mesh = extrude.extrude_profile(&square(1.0)).unwrap();
+        // Frustum from 2x2 to 1.5x1.5
+        let expected = (4.0 + 2.25 + (4.0f64 * 2.25).sqrt()) / 3.0;
+        assert!((closed_volume(&mesh) - expected).abs() < 1e-9);
+
+        let steep = ExtrudeOperation {
+            draft: 1.2,
+            ..Default::default()
+        };
+        assert!(matches!(steep.extrude_profile(&square(1.0)), Err(TopologyError::InvalidDraft)));
+    }
+
+    #[test]
+    fn test_revolve_region() {
+        // Rectangle touching the axis: a cylinder of radius 1, height 2
+        let rectangle = vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 2.0),
+            Point3::new(0.0, 0.0, 2.0),
+        ];
+        let revolve = RevolveOperation {
+            segments: 64,
+            ..Default::default()
+        };
+        let mesh = revolve.revolve_region(&ProfileRegion::new(rectangle.clone())).unwrap();
+        let polygon_area = 0.5 * 64.0 * (2.0 * PI / 64.0).sin();
+        assert!((closed_volume(&mesh) - polygon_area * 2.0).abs() < 1e-9);
+
+        // Quarter turn of a ring section is capped at both ends
+        let quarter = RevolveOperation {
+            angle: PI / 2.0,
+            segments: 16,
+            ..Default::default()
+        };
+        let section = vec![
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 1.0),
+        ];
+        let mesh = quarter.revolve_region(&ProfileRegion::new(section)).unwrap();
+        assert!(closed_volume(&mesh) > 0.0);
+
+        let crossing = vec![
+            Point3::new(-1.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 1.0),
+        ];
+        assert!(matches!(
+            revolve.revolve_region(&ProfileRegion::new(crossing)),
+            Err(TopologyError::ProfileCrossesAxis)
+        ));
+    }
+
+    #[test]
+    fn test_sweep_twist_and_curve() {
+        let straight = NurbsCurve::new(
+            1,
+            vec![Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, 3.0)],
+            vec![1.0, 1.0],
+        )
+        .unwrap();
+        let sweep = SweepOperation {
+            twist: PI / 2.0,
+            samples: 16,
+            ..SweepOperation::new(straight)
+        };
+        let mesh = sweep.sweep_profile(&square(0.5)).unwrap();
+        assert!(closed_volume(&mesh) > 0.0);
+
+        let bend = NurbsCurve::new(
+            2,
+            vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(0.0, 0.0, 5.0),
+                Point3::new(5.0, 0.0, 5.0),
+            ],
+            vec![1.0, 1.0, 1.0],
+        )
+        .unwrap();
+        let region = ProfileRegion::new(square(0.5)).with_hole(square(0.25));
+        let mesh = SweepOperation::new(bend).sweep_region(&region).unwrap();
+        assert!(closed_volume(&mesh) > 0.0);
+    }
+
+    #[test]
+    fn test_loft_guided() {
+        let profiles = vec![
+            square(1.0),
+            square(1.0).into_iter().map(|p| p + Vector3::new(0.0, 0.0, 4.0)).collect(),
+        ];
+        let plain = LoftOperation {
+            profiles: profiles.clone(),
+            ..Default::default()
+        };
+        let plain_volume = closed_volume(&plain.loft().unwrap());
+        assert!((plain_volume - 16.0).abs() < 1e-9);
+
+        // A guide bowing out through the first corner swells the loft
+        let guide = NurbsCurve::new(
+            2,
+            vec![
+                Point3::new(-1.0, -1.0, 0.0),
+                Point3::new(-3.0, -3.0, 2.0),
+                Point3::new(-1.0, -1.0, 4.0),
+            ],
+            vec![1.0, 1.0, 1.0],
+        )
+        .unwrap();
+        let guided = LoftOperation {
+            profiles,
+            guides: vec![LoftGuide { vertex: 0, curve: guide }],
+            ..Default::default()
+        };
+        assert!(closed_volume(&guided.loft().unwrap()) > plain_volume);
+    }
+
+    #[test]
+    fn test_hollow_box() {
+        let solid = ExtrudeOperation {
+            direction: Vector3::new(0.0, 0.0, 2.0),
+            capped: true,
+            ..Default::default()
+        }
+        .extrude_profile(&square(1.0))
+        .unwrap();
+        let shell = ShellOperation { thickness: 0.2, ..Default::default() };
+
+        // Closed cavity: a 1.6 cube inside the 2 cube
+        let hollow = shell.hollow(&solid).unwrap();
+        assert!((closed_volume(&hollow) - (8.0 - 1.6f64.powi(3))).abs() < 1e-9);
+
+        // Open at the top, the cavity reaches the top plane
+        let top = faces_facing(&solid, &Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(top.len(), 1);
+        let open = shell.clone().with_open_faces(top.clone()).hollow(&solid).unwrap();
+        assert!((closed_volume(&open) - (8.0 - 1.6 * 1.6 * 1.8)).abs() < 1e-9);
+
+        // Outward, the original becomes the inner wall
+        let outward = ShellOperation { outward: true, ..shell.clone() }.hollow(&solid).unwrap();
+        assert!((closed_volume(&outward) - (2.4f64.powi(3) - 8.0)).abs() < 1e-9);
+
+        // Too thick: the walls meet, and every face is reported
+        let thick = ShellOperation { thickness: 1.2, ..shell.clone() };
+        let failures = thick.check(&solid).unwrap();
+        assert_eq!(failures.len(), 6);
+        assert!(failures.iter().all(|f| f.reason == ShellFailureReason::Collapsed));
+        assert!(matches!(thick.hollow(&solid), Err(TopologyError::ShellFailed(f)) if f.len() == 6));
+
+        let everything = shell.clone().with_open_faces(solid.face_handles());
+        assert!(matches!(everything.hollow(&solid), Err(TopologyError::NothingToShell)));
+        let zero = ShellOperation { thickness: 0.0, ..shell };
+        assert!(matches!(zero.hollow(&solid), Err(TopologyError::InvalidThickness)));
+    }
+
+    #[test]
+    fn test_shell_radius_checks() {
+        let circle: Vec<Point3> = (0..32)
+            .map(|i| {
+                let angle = i as f64 * 2.0 * PI / 32.0;
+                Point3::new(angle.cos(), angle.sin(), 0.0)
+            })
+            .collect();
+        let cylinder = ExtrudeOperation {
+            direction: Vector3::new(0.0, 0.0, 1.0),
+            capped: true,
+            ..Default::default()
+        }
+        .extrude_profile(&circle)
+        .unwrap();
+        let caps: Vec<FaceHandle> = [1.0, -1.0]
+            .iter()
+            .flat_map(|z| faces_facing(&cylinder, &Vector3::new(0.0, 0.0, *z)))
+            .collect();
+
+        // A tube open at both ends
+        let tube = ShellOperation { thickness: 0.3, ..Default::default() }.with_open_faces(caps.clone());
+        let hollow = tube.hollow(&cylinder).unwrap();
+        assert!(MeshValidity::check(&hollow).unwrap().is_valid());
+
+        // The side faces can't keep a 0.8 inner radius; the caps aren't curved
+        let strict = ShellOperation { min_radius: 0.8, ..tube.clone() };
+        let failures = strict.check(&cylinder).unwrap();
+        assert_eq!(failures.len(), 32);
+        assert!(failures.iter().all(|f| !caps.contains(&f.face)));
+        assert!(matches!(
+            failures[0].reason,
+            ShellFailureReason::RadiusTooSmall { radius, offset_radius }
+                if (radius - 1.0).abs() < 0.01 && offset_radius < 0.8
+        ));
+        let error = strict.hollow(&cylinder).unwrap_err().to_string();
+        assert!(error.starts_with("Shell fails: face "), "{}", error);
+    }
 }
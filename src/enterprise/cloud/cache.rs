@@ -2,6 +2,14 @@
 //!
 //! This module provides an LRU-based local cache for cloud files with offline
 //! mode support, intelligent cache invalidation, and automatic size management.
+//!
+//! With a [`WorkspaceVault`] attached, cached files are sealed with the
+//! user's workspace key before they reach the disk and decrypted on read,
+//! each bound to its cache key.
+//!
+//! With a shared [`Resilience`] layer attached, cache misses are downloaded
+//! with its retries and hedging, and the cache treats itself as offline
+//! whenever cloud storage is reported unreachable.
 
 use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
@@ -11,6 +19,8 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
 use super::storage::{CloudStorage, FileMetadata};
+use crate::enterprise::resilience::{Resilience, CLOUD_STORAGE};
+use crate::io::vault::{VaultError, WorkspaceVault};
 
 /// Cache error types
 #[derive(Debug, thiserror::Error)]
@@ -33,6 +43,9 @@ pub enum CacheError {
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    #[error("Encryption error: {0}")]
+    Vault(#[from] VaultError),
+
     #[error("{0}")]
     Other(String),
 }
@@ -166,6 +179,8 @@ pub struct CloudCache<S: CloudStorage> {
     lru_queue: RwLock<VecDeque<String>>,
     stats: RwLock<CacheStats>,
     offline_mode: RwLock<bool>,
+    vault: Option<Arc<WorkspaceVault>>,
+    resilience: Option<Arc<Resilience>>,
 }
 
 impl<S: CloudStorage + Send + Sync + 'static> CloudCache<S> {
@@ -187,6 +202,8 @@ impl<S: CloudStorage + Send + Sync + 'static> CloudCache<S> {
             lru_queue: RwLock::new(VecDeque::new()),
             stats: RwLock::new(CacheStats::default()),
             offline_mode: RwLock::new(false),
+            vault: None,
+            resilience: None,
         };
 
         // Start periodic cleanup
@@ -200,6 +217,23 @@ impl<S: CloudStorage + Send + Sync + 'static> CloudCache<S> {
         Ok(cache)
     }
 
+    /// Encrypt cached files at rest with a workspace key
+    pub fn with_vault(mut self, vault: Arc<WorkspaceVault>) -> Self {
+        self.vault = Some(vault);
+        self
+    }
+
+    /// Whether cached files are encrypted at rest
+    pub fn is_encrypted(&self) -> bool {
+        self.vault.is_some()
+    }
+
+    /// Download through a shared resiliency layer
+    pub fn with_resilience(mut self, resilience: Arc<Resilience>) -> Self {
+        self.resilience = Some(resilience);
+        self
+    }
+
     /// Get a file from cache or cloud storage
     pub async fn get(&self, key: &str) -> Result<Vec<u8>, CacheError> {
         // Check cache first
@@ -213,8 +247,8 @@ impl<S: CloudStorage + Send + Sync + 'static> CloudCache<S> {
         self.stats.write().await.misses += 1;
         self.stats.write().await.update_hit_rate();
 
-        // If offline mode, return error
-        if *self.offline_mode.read().await {
+        // If offline, return error
+        if self.is_offline().await {
             return Err(CacheError::NotFound(format!(
                 "File not in cache and offline mode is enabled: {}",
                 key
@@ -222,10 +256,16 @@ impl<S: CloudStorage + Send + Sync + 'static> CloudCache<S> {
         }
 
         // Fetch from cloud storage
-        let data = self.storage
-            .download_file(key)
-            .await
-            .map_err(|e| CacheError::Other(e.to_string()))?;
+        let data = match &self.resilience {
+            Some(resilience) => resilience
+                .read(CLOUD_STORAGE, || self.storage.download_file(key))
+                .await
+                .map_err(|e| CacheError::Other(e.to_string()))?,
+            None => self.storage
+                .download_file(key)
+                .await
+                .map_err(|e| CacheError::Other(e.to_string()))?,
+        };
 
         // Cache the data
         self.put(key, &data).await?;
@@ -266,6 +306,12 @@ impl<S: CloudStorage + Send + Sync + 'static> CloudCache<S> {
                 )));
             }
 
+            // Decrypt if sealed
+            let data = match &self.vault {
+                Some(vault) => vault.unseal(key, &data)?,
+                None => data,
+            };
+
             // Decompress if needed
             let decompressed = if self.config.read().await.enable_compression {
                 self.decompress(&data)?
@@ -301,6 +347,12 @@ impl<S: CloudStorage + Send + Sync + 'static> CloudCache<S> {
             data.to_vec()
         };
 
+        // Seal before anything reaches the disk
+        let cache_data = match &self.vault {
+            Some(vault) => vault.seal(key, &cache_data)?,
+            None => cache_data,
+        };
+
         // Generate cache file path
         let cache_path = config.cache_dir.join(self.sanitize_key(key));
 
@@ -336,7 +388,7 @@ impl<S: CloudStorage + Send + Sync + 'static> CloudCache<S> {
         drop(config);
 
         // Create cache entry
-        let _entry = CacheEntry {
+        let entry = CacheEntry {
             key: key.to_string(),
             cache_path,
             metadata,
@@ -495,9 +547,13 @@ impl<S: CloudStorage + Send + Sync + 'static> CloudCache<S> {
         log::info!("Offline mode: {}", if enabled { "enabled" } else { "disabled" });
     }
 
-    /// Check if offline mode is enabled
+    /// Check if offline mode is enabled, or cloud storage is unreachable
     pub async fn is_offline(&self) -> bool {
         *self.offline_mode.read().await
+            || self
+                .resilience
+                .as_ref()
+                .is_some_and(|r| r.monitor().is_offline(CLOUD_STORAGE))
     }
 
     /// Get cache statistics
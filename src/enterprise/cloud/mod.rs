@@ -8,7 +8,9 @@
 //! - **Backup System**: Incremental and full backups with verification
 //! - **Versioning**: Complete version control with branching and merging
 //! - **Transfer Management**: Efficient chunked transfers with resume capability
-//! - **Local Cache**: LRU cache with offline mode support
+//! - **Local Cache**: LRU cache with offline mode support and encryption at rest
+//! - **Backup & Restore**: Encrypted scheduled backups of documents, metadata and
+//!   event streams with integrity manifests and verified point-in-time restore
 //!
 //! # Examples
 //!
@@ -26,6 +28,7 @@
 
 pub mod backup;
 pub mod cache;
+pub mod recovery;
 pub mod storage;
 pub mod sync;
 pub mod transfer;
@@ -34,6 +37,10 @@ pub mod versioning;
 // Re-export commonly used types
 pub use backup::{BackupEngine, BackupConfig, BackupType, RecoveryPoint};
 pub use cache::{CloudCache, CacheConfig, CachePolicy};
+pub use recovery::{
+    BackupService, BackupSource, EventStoreSource, RecoveryConfig, RestorePlan, SourceKind,
+    VerificationReport,
+};
 pub use storage::{CloudStorage, S3Storage, AzureBlobStorage, GCSStorage, StorageError};
 pub use sync::{SyncEngine, SyncConfig, ConflictStrategy, SyncState};
 pub use transfer::{TransferManager, TransferConfig, TransferProgress, ChunkInfo};
@@ -9,6 +9,8 @@ use std::time::SystemTime;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+use crate::enterprise::resilience::{Classify, FailureKind};
+
 /// Storage error types
 #[derive(Debug, thiserror::Error)]
 pub enum StorageError {
@@ -43,6 +45,16 @@ pub enum StorageError {
     Other(String),
 }
 
+impl Classify for StorageError {
+    fn failure_kind(&self) -> FailureKind {
+        match self {
+            Self::Network(_) => FailureKind::Transient,
+            Self::Io(e) => e.failure_kind(),
+            _ => FailureKind::Permanent,
+        }
+    }
+}
+
 /// File metadata in cloud storage
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileMetadata {
@@ -12,6 +12,8 @@
 //! - **Binary Protocol**: Efficient message serialization for low latency
 //! - **WebSocket Transport**: Reliable transport with reconnection and state recovery
 //! - **Fine-Grained Permissions**: Edit, view-only, and region-locking capabilities
+//! - **Share Links**: Expiring, revocable read-only or comment-only review
+//!   links to a document revision, with every use in the access history
 //!
 //! # Architecture
 //!
@@ -59,6 +61,7 @@ pub mod crdt;
 pub mod sync_engine;
 pub mod versioning;
 pub mod conflict_resolver;
+pub mod sharing;
 
 // Re-export commonly used types
 pub use session::{
@@ -99,7 +102,12 @@ pub use conflict_resolver::{
     Conflict, ConflictResolution, ConflictResolver, ConflictResolverConfig, ConflictSeverity,
     ConflictStatistics, ConflictType, ResolutionStrategy,
 };
+pub use sharing::{
+    share_url, ShareAccess, ShareError, ShareGrant, ShareLink, ShareLinkManager, ShareRequest,
+    ShareResult,
+};
 
+use crate::enterprise::resilience::{Classify, FailureKind};
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -152,6 +160,17 @@ pub enum CollaborationError {
     Unknown(String),
 }
 
+impl Classify for CollaborationError {
+    fn failure_kind(&self) -> FailureKind {
+        match self {
+            Self::Connection(_) => FailureKind::Unreachable,
+            Self::Transport(_) | Self::Timeout(_) => FailureKind::Transient,
+            Self::Io(e) => e.failure_kind(),
+            _ => FailureKind::Permanent,
+        }
+    }
+}
+
 /// Result type for collaboration operations
 pub type Result<T> = std::result::Result<T, CollaborationError>;
 
@@ -0,0 +1,403 @@
+//! Share links for design review
+//!
+//! A share link is a tokenized URL that opens one revision of a document
+//! in the web streaming viewer without an account. Links are read-only or
+//! comment-only, expire after a set time and can be revoked at any point.
+//!
+//! Only a SHA-256 hash of each token is kept, so a copy of the link store
+//! cannot be turned back into working URLs. Creating a link is recorded in
+//! the document's [`AccessHistory`] as a share by its creator, and every
+//! opening as a view by the link, next to the document's other accesses.
+
+use crate::enterprise::compliance::access::{AccessHistory, DocumentAccess, DocumentAction};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use super::permissions::{EditPermission, ViewMode};
+use super::versioning::VersionId;
+
+/// Lifetime of a link created without an explicit expiry
+pub const DEFAULT_SHARE_LIFETIME_DAYS: i64 = 7;
+
+/// Longest lifetime a link may be given by default
+pub const MAX_SHARE_LIFETIME_DAYS: i64 = 90;
+
+/// Random bytes in a share token
+const TOKEN_BYTES: usize = 32;
+
+/// Share link errors
+#[derive(Debug, Error)]
+pub enum ShareError {
+    /// Unknown token or link; the two are not told apart so tokens cannot
+    /// be probed
+    #[error("Share link not found")]
+    NotFound,
+
+    #[error("Share link expired at {0}")]
+    Expired(DateTime<Utc>),
+
+    #[error("Share link was revoked at {0}")]
+    Revoked(DateTime<Utc>),
+
+    #[error("Invalid share link expiry: {0}")]
+    InvalidExpiry(String),
+}
+
+/// Result type for share link operations
+pub type ShareResult<T> = std::result::Result<T, ShareError>;
+
+/// What a share link lets its holder do
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareAccess {
+    /// View the drawing only
+    #[default]
+    ReadOnly,
+    /// View the drawing and leave review comments
+    CommentOnly,
+}
+
+impl ShareAccess {
+    /// Viewer mode the link opens in
+    pub fn view_mode(self) -> ViewMode {
+        match self {
+            ShareAccess::ReadOnly => ViewMode::ViewOnly,
+            ShareAccess::CommentOnly => ViewMode::Comment,
+        }
+    }
+
+    /// Permission of a viewer session opened through the link
+    pub fn permission(self) -> EditPermission {
+        match self {
+            ShareAccess::ReadOnly => EditPermission::read_only(),
+            ShareAccess::CommentOnly => EditPermission::comment_only(),
+        }
+    }
+
+    /// Snake case name, as used in the API
+    pub fn name(self) -> &'static str {
+        match self {
+            ShareAccess::ReadOnly => "read_only",
+            ShareAccess::CommentOnly => "comment_only",
+        }
+    }
+}
+
+/// A link granting access to one document revision
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShareLink {
+    /// Link ID, used to list and revoke it; not a secret
+    pub id: Uuid,
+    /// Document shared
+    pub document_id: Uuid,
+    /// Revision the link opens, whatever has been saved since
+    pub revision: VersionId,
+    /// What the holder may do
+    pub access: ShareAccess,
+    /// Optional note for the link list, e.g. who it was sent to
+    pub label: Option<String>,
+    /// User who created the link
+    pub created_by: String,
+    /// When the link was created
+    pub created_at: DateTime<Utc>,
+    /// When the link stops working
+    pub expires_at: DateTime<Utc>,
+    /// When the link was revoked, if it was
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// User who revoked the link
+    pub revoked_by: Option<String>,
+    /// Times the link was opened
+    pub open_count: u64,
+    /// When the link was last opened
+    pub last_opened_at: Option<DateTime<Utc>>,
+    /// SHA-256 of the token
+    #[serde(skip)]
+    token_hash: [u8; 32],
+}
+
+impl ShareLink {
+    /// Whether the link has expired at `now`
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Whether the link still opens at `now`
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && !self.is_expired_at(now)
+    }
+
+    /// Name the link's accesses are recorded under
+    pub fn accessor(&self) -> String {
+        format!("share:{}", self.id)
+    }
+}
+
+/// What the holder of a link may open, handed to the viewer session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareGrant {
+    /// Link opened
+    pub link_id: Uuid,
+    /// Document to stream
+    pub document_id: Uuid,
+    /// Revision to stream
+    pub revision: VersionId,
+    /// What the holder may do
+    pub access: ShareAccess,
+    /// Permission of the viewer session
+    pub permission: EditPermission,
+    /// When the session must end
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A link to create
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareRequest {
+    /// Document to share
+    pub document_id: Uuid,
+    /// Revision to share
+    pub revision: VersionId,
+    /// What the holder may do
+    #[serde(default)]
+    pub access: ShareAccess,
+    /// When the link stops working; [`DEFAULT_SHARE_LIFETIME_DAYS`] from
+    /// now when `None`
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Optional note for the link list
+    pub label: Option<String>,
+}
+
+/// Creates, opens and revokes share links
+///
+/// Cheap to clone; clones share the same links.
+#[derive(Debug, Clone)]
+pub struct ShareLinkManager {
+    links: Arc<RwLock<HashMap<Uuid, ShareLink>>>,
+    by_token: Arc<RwLock<HashMap<[u8; 32], Uuid>>>,
+    history: AccessHistory,
+    max_lifetime: Duration,
+}
+
+impl ShareLinkManager {
+    /// Create a manager logging to `history`
+    pub fn new(history: AccessHistory) -> Self {
+        Self {
+            links: Arc::default(),
+            by_token: Arc::default(),
+            history,
+            max_lifetime: Duration::days(MAX_SHARE_LIFETIME_DAYS),
+        }
+    }
+
+    /// Set the longest lifetime a link may be given
+    pub fn with_max_lifetime(mut self, max_lifetime: Duration) -> Self {
+        self.max_lifetime = max_lifetime;
+        self
+    }
+
+    /// Create a link, returning it with its token
+    ///
+    /// The token is only available here; lost tokens cannot be recovered,
+    /// only replaced by a new link.
+    pub async fn create(&self, request: ShareRequest, created_by: &str) -> ShareResult<(ShareLink, String)> {
+        let now = Utc::now();
+        let expires_at = request
+            .expires_at
+            .unwrap_or_else(|| now + Duration::days(DEFAULT_SHARE_LIFETIME_DAYS));
+        if expires_at <= now {
+            return Err(ShareError::InvalidExpiry("expiry is in the past".to_string()));
+        }
+        if expires_at - now > self.max_lifetime {
+            return Err(ShareError::InvalidExpiry(format!(
+                "links may last at most {} days",
+                self.max_lifetime.num_days()
+            )));
+        }
+
+        let mut bytes = [0u8; TOKEN_BYTES];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let token = general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+        let link = ShareLink {
+            id: Uuid::new_v4(),
+            document_id: request.document_id,
+            revision: request.revision,
+            access: request.access,
+            label: request.label,
+            created_by: created_by.to_string(),
+            created_at: now,
+            expires_at,
+            revoked_at: None,
+            revoked_by: None,
+            open_count: 0,
+            last_opened_at: None,
+            token_hash: hash_token(&token),
+        };
+
+        self.by_token.write().await.insert(link.token_hash, link.id);
+        self.links.write().await.insert(link.id, link.clone());
+        let access = DocumentAccess::new(link.document_id, created_by, DocumentAction::Share)
+            .at(now)
+            .detail("share_link", link.id.to_string())
+            .detail("revision", link.revision.to_string())
+            .detail("access", link.access.name());
+        self.history.record(access).await;
+
+        Ok((link, token))
+    }
+
+    /// Open the link behind `token`, logging the view with `details` such
+    /// as the client address
+    pub async fn open(&self, token: &str, details: HashMap<String, String>) -> ShareResult<ShareGrant> {
+        let now = Utc::now();
+        let id = *self
+            .by_token
+            .read()
+            .await
+            .get(&hash_token(token))
+            .ok_or(ShareError::NotFound)?;
+        let link = {
+            let mut links = self.links.write().await;
+            let link = links.get_mut(&id).ok_or(ShareError::NotFound)?;
+            if let Some(revoked_at) = link.revoked_at {
+                return Err(ShareError::Revoked(revoked_at));
+            }
+            if link.is_expired_at(now) {
+                return Err(ShareError::Expired(link.expires_at));
+            }
+            link.open_count += 1;
+            link.last_opened_at = Some(now);
+            link.clone()
+        };
+
+        let mut access = DocumentAccess::new(link.document_id, link.accessor(), DocumentAction::View)
+            .at(now)
+            .detail("share_link", link.id.to_string())
+            .detail("revision", link.revision.to_string())
+            .detail("access", link.access.name());
+        access.details.extend(details);
+        self.history.record(access).await;
+
+        Ok(ShareGrant {
+            link_id: link.id,
+            document_id: link.document_id,
+            revision: link.revision,
+            access: link.access,
+            permission: link.access.permission(),
+            expires_at: link.expires_at,
+        })
+    }
+
+    /// Revoke a link; revoking it again keeps the first revocation
+    pub async fn revoke(&self, link_id: Uuid, revoked_by: &str) -> ShareResult<ShareLink> {
+        let mut links = self.links.write().await;
+        let link = links.get_mut(&link_id).ok_or(ShareError::NotFound)?;
+        if link.revoked_at.is_none() {
+            link.revoked_at = Some(Utc::now());
+            link.revoked_by = Some(revoked_by.to_string());
+        }
+        Ok(link.clone())
+    }
+
+    /// A link by ID
+    pub async fn get(&self, link_id: Uuid) -> Option<ShareLink> {
+        self.links.read().await.get(&link_id).cloned()
+    }
+
+    /// A document's links, newest first
+    pub async fn links_for(&self, document_id: Uuid) -> Vec<ShareLink> {
+        let mut links: Vec<ShareLink> = self
+            .links
+            .read()
+            .await
+            .values()
+            .filter(|link| link.document_id == document_id)
+            .cloned()
+            .collect();
+        links.sort_by_key(|link| std::cmp::Reverse(link.created_at));
+        links
+    }
+
+    /// Forget links that expired or were revoked before `cutoff`, returning
+    /// how many were removed; their accesses stay in the history
+    pub async fn purge(&self, cutoff: DateTime<Utc>) -> usize {
+        let mut links = self.links.write().await;
+        let mut by_token = self.by_token.write().await;
+        let before = links.len();
+        links.retain(|_, link| {
+            let ended = link.revoked_at.unwrap_or(link.expires_at).min(link.expires_at);
+            let keep = ended >= cutoff;
+            if !keep {
+                by_token.remove(&link.token_hash);
+            }
+            keep
+        });
+        before - links.len()
+    }
+}
+
+/// Viewer URL for a token under `base_url`
+pub fn share_url(base_url: &str, token: &str) -> String {
+    format!("{}/share/{}", base_url.trim_end_matches('/'), token)
+}
+
+fn hash_token(token: &str) -> [u8; 32] {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enterprise::compliance::access::AccessQuery;
+
+    #[tokio::test]
+    async fn test_share_link_lifecycle() {
+        let history = AccessHistory::new();
+        let shares = ShareLinkManager::new(history.clone());
+        let document_id = Uuid::new_v4();
+        let request = ShareRequest {
+            document_id,
+            revision: Uuid::new_v4(),
+            access: ShareAccess::CommentOnly,
+            expires_at: None,
+            label: Some("Client review".to_string()),
+        };
+        let (link, token) = shares.create(request.clone(), "alice").await.unwrap();
+        assert!(share_url("https://caddy.example/", &token).ends_with(&format!("example/share/{}", token)));
+
+        let grant = shares.open(&token, HashMap::new()).await.unwrap();
+        assert_eq!(grant.revision, request.revision);
+        assert_eq!(grant.permission.view_mode, ViewMode::Comment);
+        assert!(matches!(shares.open("guess", HashMap::new()).await, Err(ShareError::NotFound)));
+
+        // The share and the view are in the document's history
+        let accesses = history.history(document_id, &AccessQuery::default()).await;
+        assert_eq!(accesses.len(), 2);
+        assert_eq!(accesses[0].user, link.accessor());
+        assert_eq!(accesses[1].action, DocumentAction::Share);
+
+        let revoked = shares.revoke(link.id, "alice").await.unwrap();
+        assert_eq!(revoked.open_count, 1);
+        assert!(matches!(shares.open(&token, HashMap::new()).await, Err(ShareError::Revoked(_))));
+        assert_eq!(shares.purge(Utc::now() + Duration::seconds(1)).await, 1);
+        assert!(matches!(shares.open(&token, HashMap::new()).await, Err(ShareError::NotFound)));
+
+        // Expiry is bounded
+        let past = ShareRequest {
+            expires_at: Some(Utc::now() - Duration::hours(1)),
+            ..request.clone()
+        };
+        assert!(matches!(shares.create(past, "alice").await, Err(ShareError::InvalidExpiry(_))));
+        let forever = ShareRequest {
+            expires_at: Some(Utc::now() + Duration::days(MAX_SHARE_LIFETIME_DAYS + 1)),
+            ..request
+        };
+        assert!(matches!(shares.create(forever, "alice").await, Err(ShareError::InvalidExpiry(_))));
+    }
+}
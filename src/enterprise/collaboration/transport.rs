@@ -4,6 +4,7 @@
 //! automatic reconnection, state recovery, and heartbeat mechanisms.
 
 use super::{CollaborationError, CollaborationMessage, MessageCodec, Result};
+use crate::enterprise::resilience::{BackoffPolicy, ConnectivityMonitor, COLLABORATION};
 use chrono::{DateTime, Duration, Utc};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
@@ -51,16 +52,21 @@ pub enum ReconnectStrategy {
         multiplier: f64,
         max_attempts: Option<usize>,
     },
+    /// Exponential backoff with jitter, on the shared resiliency schedule
+    Backoff(BackoffPolicy),
 }
 
 impl Default for ReconnectStrategy {
     fn default() -> Self {
-        Self::ExponentialBackoff {
-            initial_delay_ms: 1000,
-            max_delay_ms: 30000,
-            multiplier: 2.0,
-            max_attempts: Some(10),
-        }
+        Self::Backoff(
+            BackoffPolicy::exponential(
+                std::time::Duration::from_secs(1),
+                std::time::Duration::from_secs(30),
+                2.0,
+            )
+            .with_jitter(0.5)
+            .with_max_attempts(Some(10)),
+        )
     }
 }
 
@@ -91,9 +97,17 @@ impl ReconnectStrategy {
                     }
                 }
 
-                let delay = (*initial_delay_ms as f64) * multiplier.powi(attempt as i32);
-                let delay = delay.min(*max_delay_ms as f64) as u64;
-                Some(std::time::Duration::from_millis(delay))
+                let policy = BackoffPolicy::exponential(
+                    std::time::Duration::from_millis(*initial_delay_ms),
+                    std::time::Duration::from_millis(*max_delay_ms),
+                    *multiplier,
+                );
+                Some(policy.base_delay(attempt.min(u32::MAX as usize) as u32))
+            }
+
+            Self::Backoff(policy) => {
+                let attempt = attempt.min(u32::MAX as usize) as u32;
+                policy.should_retry(attempt).then(|| policy.delay(attempt))
             }
         }
     }
@@ -252,6 +266,7 @@ pub struct WebSocketTransport {
     state: Arc<RwLock<TransportState>>,
     tx: mpsc::UnboundedSender<CollaborationMessage>,
     rx: Arc<Mutex<mpsc::UnboundedReceiver<CollaborationMessage>>>,
+    monitor: Option<Arc<ConnectivityMonitor>>,
 }
 
 impl WebSocketTransport {
@@ -275,9 +290,16 @@ impl WebSocketTransport {
             state: Arc::new(RwLock::new(state)),
             tx,
             rx: Arc::new(Mutex::new(rx)),
+            monitor: None,
         }
     }
 
+    /// Report connection health to a shared connectivity monitor
+    pub fn with_monitor(mut self, monitor: Arc<ConnectivityMonitor>) -> Self {
+        self.monitor = Some(monitor);
+        self
+    }
+
     /// Start heartbeat task
     async fn start_heartbeat_task(&self) -> Result<()> {
         let state = self.state.clone();
@@ -355,6 +377,9 @@ impl WebSocketTransport {
                     Ok(())
                 }
                 Err(e) => {
+                    if let Some(monitor) = &self.monitor {
+                        monitor.record_failure(COLLABORATION, &e.to_string());
+                    }
                     let state = self.state.read();
                     Self::emit_event_static(&state.callbacks, TransportEvent::Error {
                         error: e.to_string(),
@@ -404,6 +429,7 @@ impl WebSocketTransport {
 #[async_trait::async_trait]
 impl Transport for WebSocketTransport {
     async fn connect(&self) -> Result<()> {
+        let started = std::time::Instant::now();
         {
             let mut state = self.state.write();
             state.connection_state = ConnectionState::Connecting;
@@ -425,6 +451,10 @@ impl Transport for WebSocketTransport {
             }
         }
 
+        if let Some(monitor) = &self.monitor {
+            monitor.record_success(COLLABORATION, started.elapsed());
+        }
+
         // Start heartbeat
         self.start_heartbeat_task().await?;
 
@@ -564,6 +594,18 @@ mod tests {
         assert_eq!(strategy.calculate_delay(4), Some(std::time::Duration::from_millis(10000)));
     }
 
+    #[test]
+    fn test_reconnect_strategy_default_has_jitter_and_limit() {
+        let strategy = ReconnectStrategy::default();
+
+        for attempt in 0..10 {
+            let delay = strategy.calculate_delay(attempt).unwrap();
+            let full = std::time::Duration::from_secs(1 << attempt).min(std::time::Duration::from_secs(30));
+            assert!(delay <= full && delay >= full / 2);
+        }
+        assert_eq!(strategy.calculate_delay(10), None);
+    }
+
     #[tokio::test]
     async fn test_transport_lifecycle() {
         let config = TransportConfig::default();
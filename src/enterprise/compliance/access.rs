@@ -0,0 +1,379 @@
+//! Per-document access history
+//!
+//! Records who viewed, edited, exported or shared each document. The
+//! history answers "who touched this drawing" for the document properties
+//! panel and the API, and its period summaries feed compliance reports.
+//!
+//! How long events are kept is set by the retention engine: the active
+//! [`RetentionPolicy`](super::retention::RetentionPolicy) for the
+//! [`ACCESS_HISTORY_CATEGORY`] data category decides when events expire,
+//! and documents under a legal hold keep their full history.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use super::retention::RetentionManager;
+
+/// Retention data category covering access history
+pub const ACCESS_HISTORY_CATEGORY: &str = "document_access";
+
+/// What was done to a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentAction {
+    /// Opened or previewed
+    View,
+    /// Modified and saved
+    Edit,
+    /// Written out to a file
+    Export,
+    /// Shared with another user or by link
+    Share,
+}
+
+impl DocumentAction {
+    /// Lowercase name, as used in the API
+    pub fn name(self) -> &'static str {
+        match self {
+            DocumentAction::View => "view",
+            DocumentAction::Edit => "edit",
+            DocumentAction::Export => "export",
+            DocumentAction::Share => "share",
+        }
+    }
+
+    /// Parse a lowercase name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "view" => Some(DocumentAction::View),
+            "edit" => Some(DocumentAction::Edit),
+            "export" => Some(DocumentAction::Export),
+            "share" => Some(DocumentAction::Share),
+            _ => None,
+        }
+    }
+}
+
+/// One access to a document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentAccess {
+    /// Unique event ID
+    pub id: Uuid,
+    /// Document accessed
+    pub document_id: Uuid,
+    /// User who accessed it
+    pub user: String,
+    /// What they did
+    pub action: DocumentAction,
+    /// When
+    pub timestamp: DateTime<Utc>,
+    /// Action-specific details, e.g. export format or share recipient
+    pub details: HashMap<String, String>,
+}
+
+impl DocumentAccess {
+    /// Access happening now
+    pub fn new(document_id: Uuid, user: impl Into<String>, action: DocumentAction) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            document_id,
+            user: user.into(),
+            action,
+            timestamp: Utc::now(),
+            details: HashMap::new(),
+        }
+    }
+
+    /// Set the time of the access
+    pub fn at(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Add a detail
+    pub fn detail(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.details.insert(key.into(), value.into());
+        self
+    }
+}
+
+/// Filter for a document's history
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccessQuery {
+    /// Only this user's accesses
+    pub user: Option<String>,
+    /// Only these actions; empty for all
+    pub actions: Vec<DocumentAction>,
+    /// Accesses at or after
+    pub since: Option<DateTime<Utc>>,
+    /// Accesses before
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl AccessQuery {
+    fn matches(&self, access: &DocumentAccess) -> bool {
+        self.user.as_deref().is_none_or(|user| access.user == user)
+            && (self.actions.is_empty() || self.actions.contains(&access.action))
+            && self.since.is_none_or(|since| access.timestamp >= since)
+            && self.until.is_none_or(|until| access.timestamp < until)
+    }
+}
+
+/// Who has touched a document, for the properties panel
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DocumentAccessSummary {
+    /// Document summarised
+    pub document_id: Uuid,
+    /// Accesses by action
+    pub counts: BTreeMap<DocumentAction, usize>,
+    /// Distinct users, alphabetically
+    pub users: Vec<String>,
+    /// Most recent view
+    pub last_viewed: Option<DocumentAccess>,
+    /// Most recent edit
+    pub last_edited: Option<DocumentAccess>,
+}
+
+/// Access history for all documents
+///
+/// Cheap to clone; clones share the same history.
+#[derive(Debug, Clone, Default)]
+pub struct AccessHistory {
+    events: Arc<RwLock<HashMap<Uuid, Vec<DocumentAccess>>>>,
+}
+
+impl AccessHistory {
+    /// Create an empty history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an access
+    pub async fn record(&self, access: DocumentAccess) -> Uuid {
+        let id = access.id;
+        let mut events = self.events.write().await;
+        let history = events.entry(access.document_id).or_default();
+        // Keep each document's events in time order even when callers
+        // report late
+        let position = history.partition_point(|e| e.timestamp <= access.timestamp);
+        history.insert(position, access);
+        id
+    }
+
+    /// A document's accesses matching `query`, newest first
+    pub async fn history(&self, document_id: Uuid, query: &AccessQuery) -> Vec<DocumentAccess> {
+        let events = self.events.read().await;
+        events
+            .get(&document_id)
+            .map(|history| history.iter().rev().filter(|e| query.matches(e)).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Counts, users and latest view and edit for a document
+    pub async fn summary(&self, document_id: Uuid) -> DocumentAccessSummary {
+        let events = self.events.read().await;
+        let mut summary = DocumentAccessSummary {
+            document_id,
+            ..Default::default()
+        };
+        let Some(history) = events.get(&document_id) else {
+            return summary;
+        };
+
+        let mut users = HashSet::new();
+        for access in history {
+            *summary.counts.entry(access.action).or_default() += 1;
+            users.insert(access.user.clone());
+        }
+        summary.users = users.into_iter().collect();
+        summary.users.sort();
+        summary.last_viewed = history.iter().rev().find(|e| e.action == DocumentAction::View).cloned();
+        summary.last_edited = history.iter().rev().find(|e| e.action == DocumentAction::Edit).cloned();
+        summary
+    }
+
+    /// Every document a user accessed in `[start, end)`, oldest first
+    pub async fn user_activity(&self, user: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<DocumentAccess> {
+        let query = AccessQuery {
+            user: Some(user.to_string()),
+            since: Some(start),
+            until: Some(end),
+            ..Default::default()
+        };
+        let events = self.events.read().await;
+        let mut accesses: Vec<DocumentAccess> = events
+            .values()
+            .flatten()
+            .filter(|e| query.matches(e))
+            .cloned()
+            .collect();
+        accesses.sort_by_key(|e| e.timestamp);
+        accesses
+    }
+
+    /// Access summary for a compliance report period
+    pub async fn generate_report(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> serde_json::Value {
+        let query = AccessQuery {
+            since: Some(start),
+            until: Some(end),
+            ..Default::default()
+        };
+        let events = self.events.read().await;
+
+        let mut by_action: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut by_user: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut documents = 0;
+        let mut total = 0;
+        for history in events.values() {
+            let mut touched = false;
+            for access in history.iter().filter(|e| query.matches(e)) {
+                *by_action.entry(access.action.name()).or_default() += 1;
+                *by_user.entry(access.user.as_str()).or_default() += 1;
+                touched = true;
+                total += 1;
+            }
+            documents += usize::from(touched);
+        }
+
+        serde_json::json!({
+            "period": { "start": start, "end": end },
+            "total_accesses": total,
+            "documents_accessed": documents,
+            "by_action": by_action,
+            "by_user": by_user,
+        })
+    }
+
+    /// Drop events the retention policy for [`ACCESS_HISTORY_CATEGORY`]
+    /// has expired, returning how many were removed
+    ///
+    /// Nothing is removed without an active policy, and documents under a
+    /// legal hold are skipped.
+    pub async fn apply_retention(&self, retention: &RetentionManager) -> usize {
+        let Some(policy) = retention.get_applicable_policy(ACCESS_HISTORY_CATEGORY).await else {
+            return 0;
+        };
+
+        let now = Utc::now();
+        let mut events = self.events.write().await;
+        let mut removed = 0;
+        for (document_id, history) in events.iter_mut() {
+            if retention
+                .is_under_legal_hold(&document_id.to_string(), ACCESS_HISTORY_CATEGORY)
+                .await
+            {
+                continue;
+            }
+            let before = history.len();
+            history.retain(|e| !policy.retention_period.is_expired(e.timestamp, now));
+            removed += before - history.len();
+        }
+        events.retain(|_, history| !history.is_empty());
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enterprise::compliance::retention::{
+        LegalHold, PostRetentionAction, RetentionPeriod, RetentionPolicy,
+    };
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn test_history_and_summary() {
+        let history = AccessHistory::new();
+        let drawing = Uuid::new_v4();
+        let start = Utc::now() - Duration::hours(3);
+
+        history.record(DocumentAccess::new(drawing, "bob", DocumentAction::Edit).at(start + Duration::hours(1))).await;
+        history.record(DocumentAccess::new(drawing, "alice", DocumentAction::View).at(start)).await;
+        history
+            .record(
+                DocumentAccess::new(drawing, "alice", DocumentAction::Export)
+                    .at(start + Duration::hours(2))
+                    .detail("format", "PDF"),
+            )
+            .await;
+        history.record(DocumentAccess::new(Uuid::new_v4(), "carol", DocumentAction::View)).await;
+
+        let all = history.history(drawing, &AccessQuery::default()).await;
+        let actions: Vec<DocumentAction> = all.iter().map(|e| e.action).collect();
+        assert_eq!(actions, [DocumentAction::Export, DocumentAction::Edit, DocumentAction::View]);
+
+        let query = AccessQuery {
+            user: Some("alice".to_string()),
+            actions: vec![DocumentAction::Export],
+            ..Default::default()
+        };
+        let exports = history.history(drawing, &query).await;
+        assert_eq!(exports.len(), 1);
+        assert_eq!(exports[0].details["format"], "PDF");
+
+        let summary = history.summary(drawing).await;
+        assert_eq!(summary.users, ["alice", "bob"]);
+        assert_eq!(summary.counts[&DocumentAction::View], 1);
+        assert_eq!(summary.last_edited.unwrap().user, "bob");
+
+        let report = history.generate_report(start, Utc::now() + Duration::seconds(1)).await;
+        assert_eq!(report["total_accesses"], 4);
+        assert_eq!(report["documents_accessed"], 2);
+        assert_eq!(report["by_user"]["alice"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_retention_respects_legal_hold() {
+        let retention = RetentionManager::new();
+        let history = AccessHistory::new();
+        let (held, free) = (Uuid::new_v4(), Uuid::new_v4());
+        let old = Utc::now() - Duration::days(400);
+        for document in [held, free] {
+            history.record(DocumentAccess::new(document, "alice", DocumentAction::View).at(old)).await;
+            history.record(DocumentAccess::new(document, "alice", DocumentAction::View)).await;
+        }
+
+        // No policy: everything is kept
+        assert_eq!(history.apply_retention(&retention).await, 0);
+
+        retention
+            .add_policy(RetentionPolicy {
+                id: Uuid::new_v4(),
+                name: "Access history".to_string(),
+                data_category: ACCESS_HISTORY_CATEGORY.to_string(),
+                retention_period: RetentionPeriod::Duration(Duration::days(365)),
+                post_retention_action: PostRetentionAction::Delete,
+                active: true,
+                legal_basis: vec![],
+                priority: 1,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                created_by: "admin".to_string(),
+            })
+            .await
+            .unwrap();
+        retention
+            .place_legal_hold(LegalHold {
+                id: Uuid::new_v4(),
+                name: "Case 7".to_string(),
+                description: "Dispute over revision B".to_string(),
+                data_categories: vec![],
+                data_items: HashSet::from([held.to_string()]),
+                started_at: Utc::now(),
+                ended_at: None,
+                custodian: "legal@example.com".to_string(),
+                case_reference: "CASE-7".to_string(),
+                active: true,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(history.apply_retention(&retention).await, 1);
+        assert_eq!(history.history(held, &AccessQuery::default()).await.len(), 2);
+        assert_eq!(history.history(free, &AccessQuery::default()).await.len(), 1);
+    }
+}
@@ -0,0 +1,853 @@
+//! Retention enforcement
+//!
+//! [`RetentionManager`] holds the policies and legal holds; this module
+//! carries them out. A [`RetentionEngine`] scans every registered
+//! [`RetentionStore`] (audit logs, documents, analytics, backups), matches
+//! each item against the active policy for its data category and then:
+//!
+//! - leaves unexpired items alone,
+//! - exempts items under a legal hold, whatever their age,
+//! - copies items whose policy says archive to cold storage and only then
+//!   removes the hot copy,
+//! - purges items whose policy says delete, hashing them first, and
+//! - lists items whose policy says review for a person to decide.
+//!
+//! Every run that purges anything issues a [`DeletionCertificate`] naming
+//! each destroyed item, its content hash, the policy and legal basis it was
+//! deleted under, and the items that were exempted. The certificate carries
+//! a digest over its contents so later tampering is detectable.
+//!
+//! [`RetentionEngine::plan`] runs the same matching without touching data,
+//! for previewing what a run would do.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use super::retention::{ArchivalStrategy, PostRetentionAction, RetentionManager};
+use super::{ComplianceError, ComplianceResult};
+use crate::enterprise::cloud::storage::CloudStorage;
+
+/// Kind of data a store holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionTarget {
+    /// Audit log entries
+    AuditLogs,
+    /// Drawings and other user documents
+    Documents,
+    /// Usage and analytics events
+    Analytics,
+    /// Backup snapshots
+    Backups,
+}
+
+impl RetentionTarget {
+    /// Stable name, used in archive paths and certificates
+    pub fn name(&self) -> &'static str {
+        match self {
+            RetentionTarget::AuditLogs => "audit_logs",
+            RetentionTarget::Documents => "documents",
+            RetentionTarget::Analytics => "analytics",
+            RetentionTarget::Backups => "backups",
+        }
+    }
+}
+
+/// An item a store holds, as seen by the engine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetainedItem {
+    /// Item ID, unique within its store
+    pub id: String,
+
+    /// Data category the retention policy is looked up by
+    pub category: String,
+
+    /// When the item was created
+    pub created_at: DateTime<Utc>,
+
+    /// Stored size in bytes
+    pub size_bytes: u64,
+}
+
+/// A source of data subject to retention
+#[async_trait]
+pub trait RetentionStore: Send + Sync {
+    /// What kind of data the store holds
+    fn target(&self) -> RetentionTarget;
+
+    /// List every item currently held
+    async fn scan(&self) -> ComplianceResult<Vec<RetainedItem>>;
+
+    /// Read an item's contents, for archiving and hashing
+    async fn read(&self, id: &str) -> ComplianceResult<Vec<u8>>;
+
+    /// Permanently remove an item
+    async fn purge(&self, id: &str) -> ComplianceResult<()>;
+}
+
+/// In-memory [`RetentionStore`]
+pub struct MemoryStore {
+    target: RetentionTarget,
+    items: RwLock<HashMap<String, (RetainedItem, Vec<u8>)>>,
+}
+
+impl MemoryStore {
+    /// Create an empty store
+    pub fn new(target: RetentionTarget) -> Self {
+        Self {
+            target,
+            items: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Add or replace an item
+    pub async fn insert(&self, mut item: RetainedItem, contents: Vec<u8>) {
+        item.size_bytes = contents.len() as u64;
+        let mut items = self.items.write().await;
+        items.insert(item.id.clone(), (item, contents));
+    }
+
+    /// Whether an item is held
+    pub async fn contains(&self, id: &str) -> bool {
+        self.items.read().await.contains_key(id)
+    }
+
+    /// Number of items held
+    pub async fn len(&self) -> usize {
+        self.items.read().await.len()
+    }
+
+    /// Whether the store is empty
+    pub async fn is_empty(&self) -> bool {
+        self.items.read().await.is_empty()
+    }
+}
+
+#[async_trait]
+impl RetentionStore for MemoryStore {
+    fn target(&self) -> RetentionTarget {
+        self.target
+    }
+
+    async fn scan(&self) -> ComplianceResult<Vec<RetainedItem>> {
+        let items = self.items.read().await;
+        Ok(items.values().map(|(item, _)| item.clone()).collect())
+    }
+
+    async fn read(&self, id: &str) -> ComplianceResult<Vec<u8>> {
+        let items = self.items.read().await;
+        items
+            .get(id)
+            .map(|(_, contents)| contents.clone())
+            .ok_or_else(|| ComplianceError::NotFound(id.to_string()))
+    }
+
+    async fn purge(&self, id: &str) -> ComplianceResult<()> {
+        let mut items = self.items.write().await;
+        items
+            .remove(id)
+            .map(|_| ())
+            .ok_or_else(|| ComplianceError::NotFound(id.to_string()))
+    }
+}
+
+/// What retention calls for on one item
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum RetentionDecision {
+    /// No policy applies or the item has not expired
+    Retain,
+    /// Expired but under one or more legal holds
+    Exempt {
+        /// Holds covering the item
+        holds: Vec<Uuid>,
+    },
+    /// Move to cold storage
+    Archive,
+    /// Purge
+    Delete,
+    /// Flag for manual review
+    Review,
+}
+
+/// One item's entry in a plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedItem {
+    /// Store the item lives in
+    pub target: RetentionTarget,
+
+    /// The item
+    pub item: RetainedItem,
+
+    /// Policy that applies, if any
+    pub policy_id: Option<Uuid>,
+
+    /// When the policy lets the item go
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// What to do
+    pub decision: RetentionDecision,
+}
+
+/// Decisions for everything the stores hold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPlan {
+    /// When the stores were scanned
+    pub generated_at: DateTime<Utc>,
+
+    /// Items in store order
+    pub items: Vec<PlannedItem>,
+}
+
+impl RetentionPlan {
+    /// Items with a given decision kind
+    pub fn with_decision(&self, decision: &RetentionDecision) -> Vec<&PlannedItem> {
+        self.items
+            .iter()
+            .filter(|p| std::mem::discriminant(&p.decision) == std::mem::discriminant(decision))
+            .collect()
+    }
+}
+
+/// An item moved to cold storage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedItem {
+    /// Store the item came from
+    pub target: RetentionTarget,
+
+    /// Item ID
+    pub id: String,
+
+    /// Path in cold storage
+    pub location: String,
+
+    /// SHA-256 of the archived contents
+    pub sha256: String,
+}
+
+/// An item a run could not process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionFailure {
+    /// Store the item lives in
+    pub target: RetentionTarget,
+
+    /// Item ID
+    pub id: String,
+
+    /// What went wrong
+    pub error: String,
+}
+
+/// An item a run destroyed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedItem {
+    /// Store the item lived in
+    pub target: RetentionTarget,
+
+    /// Item ID
+    pub id: String,
+
+    /// Data category
+    pub category: String,
+
+    /// When the item was created
+    pub created_at: DateTime<Utc>,
+
+    /// Size in bytes
+    pub size_bytes: u64,
+
+    /// SHA-256 of the contents at deletion
+    pub sha256: String,
+
+    /// Policy the item was deleted under
+    pub policy_id: Uuid,
+
+    /// Policy name at the time
+    pub policy_name: String,
+
+    /// Legal or regulatory basis the policy cites
+    pub legal_basis: Vec<String>,
+
+    /// When it was purged
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// An expired item kept because of a legal hold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exemption {
+    /// Store the item lives in
+    pub target: RetentionTarget,
+
+    /// Item ID
+    pub id: String,
+
+    /// Holds covering the item
+    pub holds: Vec<Uuid>,
+}
+
+/// Signed record of what a retention run destroyed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletionCertificate {
+    /// Certificate ID
+    pub id: Uuid,
+
+    /// Run that issued it
+    pub run_id: Uuid,
+
+    /// Issue time
+    pub issued_at: DateTime<Utc>,
+
+    /// Operator the run executed as
+    pub issued_by: String,
+
+    /// Destroyed items
+    pub items: Vec<DeletedItem>,
+
+    /// Expired items kept under legal hold
+    pub exemptions: Vec<Exemption>,
+
+    /// SHA-256 over the other fields
+    pub digest: String,
+}
+
+impl DeletionCertificate {
+    fn compute_digest(&self) -> String {
+        let body = serde_json::json!({
+            "id": self.id,
+            "run_id": self.run_id,
+            "issued_at": self.issued_at,
+            "issued_by": self.issued_by,
+            "items": self.items,
+            "exemptions": self.exemptions,
+        });
+        hex::encode(Sha256::digest(body.to_string().as_bytes()))
+    }
+
+    /// Whether the contents still match the digest
+    pub fn verify(&self) -> bool {
+        self.digest == self.compute_digest()
+    }
+
+    /// Total bytes destroyed
+    pub fn total_bytes(&self) -> u64 {
+        self.items.iter().map(|i| i.size_bytes).sum()
+    }
+}
+
+/// Outcome of one retention run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionRun {
+    /// Run ID
+    pub id: Uuid,
+
+    /// Start time
+    pub started_at: DateTime<Utc>,
+
+    /// End time
+    pub finished_at: DateTime<Utc>,
+
+    /// Items scanned across all stores
+    pub scanned: usize,
+
+    /// Items moved to cold storage
+    pub archived: Vec<ArchivedItem>,
+
+    /// Number of items purged
+    pub deleted: usize,
+
+    /// Expired items kept under legal hold
+    pub exempted: usize,
+
+    /// Items flagged for manual review, as `target/id`
+    pub review: Vec<String>,
+
+    /// Items that could not be processed; they are left in place
+    pub failures: Vec<RetentionFailure>,
+
+    /// Certificate for the purged items, if any were purged
+    pub certificate: Option<DeletionCertificate>,
+}
+
+/// Carries out retention policies against registered stores
+pub struct RetentionEngine {
+    /// Stores scanned on each run
+    stores: Vec<Arc<dyn RetentionStore>>,
+
+    /// Cold storage and the strategy used for archiving
+    archive: Option<(Arc<dyn CloudStorage>, ArchivalStrategy)>,
+
+    /// Most archive and delete actions per run
+    batch_size: usize,
+
+    /// Operator runs execute as
+    operator: String,
+
+    /// Issued certificates
+    certificates: Arc<RwLock<Vec<DeletionCertificate>>>,
+}
+
+impl RetentionEngine {
+    /// Create an engine with no stores
+    pub fn new() -> Self {
+        Self {
+            stores: Vec::new(),
+            archive: None,
+            batch_size: 1000,
+            operator: "retention_engine".to_string(),
+            certificates: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// Scan a store on each run
+    pub fn with_store(mut self, store: Arc<dyn RetentionStore>) -> Self {
+        self.stores.push(store);
+        self
+    }
+
+    /// Archive to cold storage under the strategy's storage location
+    pub fn with_archive(mut self, storage: Arc<dyn CloudStorage>, strategy: ArchivalStrategy) -> Self {
+        self.archive = Some((storage, strategy));
+        self
+    }
+
+    /// Limit archive and delete actions per run; the rest wait for the next
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Operator named in certificates
+    pub fn with_operator(mut self, operator: impl Into<String>) -> Self {
+        self.operator = operator.into();
+        self
+    }
+
+    /// Decide what a run would do, without touching any data
+    pub async fn plan(&self, retention: &RetentionManager) -> ComplianceResult<RetentionPlan> {
+        let now = Utc::now();
+        let mut items = Vec::new();
+        for store in &self.stores {
+            for item in store.scan().await? {
+                items.push(self.decide(store.target(), item, retention, now).await);
+            }
+        }
+        Ok(RetentionPlan {
+            generated_at: now,
+            items,
+        })
+    }
+
+    async fn decide(
+        &self,
+        target: RetentionTarget,
+        item: RetainedItem,
+        retention: &RetentionManager,
+        now: DateTime<Utc>,
+    ) -> PlannedItem {
+        let policy = retention.get_applicable_policy(&item.category).await;
+        let expires_at = policy
+            .as_ref()
+            .and_then(|p| p.retention_period.calculate_expiration(item.created_at));
+
+        let decision = match &policy {
+            Some(p) if p.retention_period.is_expired(item.created_at, now) => {
+                let holds = retention.holds_for(&item.id, &item.category).await;
+                if !holds.is_empty() {
+                    RetentionDecision::Exempt { holds }
+                } else {
+                    match p.post_retention_action {
+                        PostRetentionAction::Delete => RetentionDecision::Delete,
+                        PostRetentionAction::Archive => RetentionDecision::Archive,
+                        PostRetentionAction::Review => RetentionDecision::Review,
+                        PostRetentionAction::NoAction => RetentionDecision::Retain,
+                    }
+                }
+            }
+            _ => RetentionDecision::Retain,
+        };
+
+        PlannedItem {
+            target,
+            item,
+            policy_id: policy.map(|p| p.id),
+            expires_at,
+            decision,
+        }
+    }
+
+    /// Scan, archive and purge
+    ///
+    /// Failures on single items are collected in the run and leave the item
+    /// in place; only a store that cannot be scanned fails the run.
+    pub async fn execute(&self, retention: &RetentionManager) -> ComplianceResult<RetentionRun> {
+        let started_at = Utc::now();
+        let run_id = Uuid::new_v4();
+        let plan = self.plan(retention).await?;
+
+        let mut run = RetentionRun {
+            id: run_id,
+            started_at,
+            finished_at: started_at,
+            scanned: plan.items.len(),
+            archived: Vec::new(),
+            deleted: 0,
+            exempted: 0,
+            review: Vec::new(),
+            failures: Vec::new(),
+            certificate: None,
+        };
+        let mut deleted = Vec::new();
+        let mut exemptions = Vec::new();
+        let mut actions = 0;
+
+        for planned in plan.items {
+            let target = planned.target;
+            let id = planned.item.id.clone();
+            match planned.decision {
+                RetentionDecision::Retain => {}
+                RetentionDecision::Exempt { holds } => {
+                    run.exempted += 1;
+                    exemptions.push(Exemption { target, id, holds });
+                }
+                RetentionDecision::Review => run.review.push(format!("{}/{}", target.name(), id)),
+                RetentionDecision::Archive | RetentionDecision::Delete if actions >= self.batch_size => {}
+                RetentionDecision::Archive => {
+                    actions += 1;
+                    match self.archive_item(&planned, retention).await {
+                        Ok(archived) => run.archived.push(archived),
+                        Err(e) => run.failures.push(RetentionFailure {
+                            target,
+                            id,
+                            error: e.to_string(),
+                        }),
+                    }
+                }
+                RetentionDecision::Delete => {
+                    actions += 1;
+                    match self.delete_item(&planned, retention).await {
+                        Ok(item) => deleted.push(item),
+                        Err(e) => run.failures.push(RetentionFailure {
+                            target,
+                            id,
+                            error: e.to_string(),
+                        }),
+                    }
+                }
+            }
+        }
+
+        run.deleted = deleted.len();
+        if !deleted.is_empty() {
+            let mut certificate = DeletionCertificate {
+                id: Uuid::new_v4(),
+                run_id,
+                issued_at: Utc::now(),
+                issued_by: self.operator.clone(),
+                items: deleted,
+                exemptions,
+                digest: String::new(),
+            };
+            certificate.digest = certificate.compute_digest();
+            self.certificates.write().await.push(certificate.clone());
+            run.certificate = Some(certificate);
+        }
+        run.finished_at = Utc::now();
+        Ok(run)
+    }
+
+    fn store(&self, target: RetentionTarget) -> ComplianceResult<&Arc<dyn RetentionStore>> {
+        self.stores
+            .iter()
+            .find(|s| s.target() == target)
+            .ok_or_else(|| ComplianceError::NotFound(format!("store for {}", target.name())))
+    }
+
+    /// Copy to cold storage, then drop the hot copy
+    async fn archive_item(&self, planned: &PlannedItem, retention: &RetentionManager) -> ComplianceResult<ArchivedItem> {
+        let Some((storage, strategy)) = &self.archive else {
+            return Err(ComplianceError::RetentionError("no cold storage configured".to_string()));
+        };
+        let item = &planned.item;
+        let store = self.store(planned.target)?;
+        let contents = store.read(&item.id).await?;
+        let sha256 = hex::encode(Sha256::digest(&contents));
+
+        let location = format!(
+            "{}/{}/{}",
+            strategy.storage_location.trim_end_matches('/'),
+            planned.target.name(),
+            item.id
+        );
+        storage
+            .upload_file(&location, &contents)
+            .await
+            .map_err(|e| ComplianceError::RetentionError(format!("archive upload failed: {}", e)))?;
+
+        // A hold placed since the plan was made keeps the hot copy too
+        if retention.is_under_legal_hold(&item.id, &item.category).await {
+            return Err(ComplianceError::RetentionError("placed under legal hold during run".to_string()));
+        }
+        store.purge(&item.id).await?;
+        retention.mark_archived(&item.id, location.clone()).await.ok();
+
+        Ok(ArchivedItem {
+            target: planned.target,
+            id: item.id.clone(),
+            location,
+            sha256,
+        })
+    }
+
+    async fn delete_item(&self, planned: &PlannedItem, retention: &RetentionManager) -> ComplianceResult<DeletedItem> {
+        let item = &planned.item;
+        let policy = match planned.policy_id {
+            Some(id) => retention.get_policy(id).await,
+            None => None,
+        }
+        .ok_or_else(|| ComplianceError::RetentionError("policy no longer exists".to_string()))?;
+
+        if retention.is_under_legal_hold(&item.id, &item.category).await {
+            return Err(ComplianceError::RetentionError("placed under legal hold during run".to_string()));
+        }
+        let store = self.store(planned.target)?;
+        let contents = store.read(&item.id).await?;
+        store.purge(&item.id).await?;
+        retention.mark_deleted(&item.id).await.ok();
+
+        Ok(DeletedItem {
+            target: planned.target,
+            id: item.id.clone(),
+            category: item.category.clone(),
+            created_at: item.created_at,
+            size_bytes: contents.len() as u64,
+            sha256: hex::encode(Sha256::digest(&contents)),
+            policy_id: policy.id,
+            policy_name: policy.name,
+            legal_basis: policy.legal_basis,
+            deleted_at: Utc::now(),
+        })
+    }
+
+    /// All certificates issued, oldest first
+    pub async fn certificates(&self) -> Vec<DeletionCertificate> {
+        self.certificates.read().await.clone()
+    }
+
+    /// Certificate by ID
+    pub async fn get_certificate(&self, id: Uuid) -> Option<DeletionCertificate> {
+        let certificates = self.certificates.read().await;
+        certificates.iter().find(|c| c.id == id).cloned()
+    }
+}
+
+impl Default for RetentionEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enterprise::cloud::storage::{FileMetadata, StorageError, StorageStats};
+    use crate::enterprise::compliance::retention::{LegalHold, RetentionPeriod, RetentionPolicy};
+    use chrono::Duration;
+    use std::collections::HashSet;
+
+    #[derive(Default)]
+    struct ColdStorage {
+        files: RwLock<HashMap<String, Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl CloudStorage for ColdStorage {
+        async fn upload_file(&self, path: &str, data: &[u8]) -> Result<FileMetadata, StorageError> {
+            self.files.write().await.insert(path.to_string(), data.to_vec());
+            Ok(FileMetadata {
+                path: path.to_string(),
+                size: data.len() as u64,
+                modified: std::time::SystemTime::now(),
+                hash: String::new(),
+                version: 1,
+                content_type: None,
+                custom_metadata: HashMap::new(),
+            })
+        }
+
+        async fn download_file(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+            self.files
+                .read()
+                .await
+                .get(path)
+                .cloned()
+                .ok_or_else(|| StorageError::FileNotFound(path.to_string()))
+        }
+
+        async fn delete_file(&self, path: &str) -> Result<(), StorageError> {
+            self.files.write().await.remove(path);
+            Ok(())
+        }
+
+        async fn list_files(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+            let files = self.files.read().await;
+            Ok(files.keys().filter(|k| k.starts_with(prefix)).cloned().collect())
+        }
+
+        async fn get_metadata(&self, path: &str) -> Result<FileMetadata, StorageError> {
+            Err(StorageError::FileNotFound(path.to_string()))
+        }
+
+        async fn file_exists(&self, path: &str) -> Result<bool, StorageError> {
+            Ok(self.files.read().await.contains_key(path))
+        }
+
+        async fn copy_file(&self, _source: &str, _destination: &str) -> Result<(), StorageError> {
+            Ok(())
+        }
+
+        async fn move_file(&self, _source: &str, _destination: &str) -> Result<(), StorageError> {
+            Ok(())
+        }
+
+        async fn get_stats(&self) -> Result<StorageStats, StorageError> {
+            Ok(StorageStats::default())
+        }
+
+        async fn create_presigned_url(&self, path: &str, _expiry_secs: u64) -> Result<String, StorageError> {
+            Ok(path.to_string())
+        }
+    }
+
+    fn policy(category: &str, days: i64, action: PostRetentionAction) -> RetentionPolicy {
+        RetentionPolicy {
+            id: Uuid::new_v4(),
+            name: format!("{} retention", category),
+            data_category: category.to_string(),
+            retention_period: RetentionPeriod::Duration(Duration::days(days)),
+            post_retention_action: action,
+            active: true,
+            legal_basis: vec!["Records schedule 4.2".to_string()],
+            priority: 1,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            created_by: "admin".to_string(),
+        }
+    }
+
+    fn item(id: &str, category: &str, age_days: i64) -> RetainedItem {
+        RetainedItem {
+            id: id.to_string(),
+            category: category.to_string(),
+            created_at: Utc::now() - Duration::days(age_days),
+            size_bytes: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_archives_purges_and_certifies() {
+        let retention = RetentionManager::new();
+        retention.add_policy(policy("audit", 365, PostRetentionAction::Archive)).await.unwrap();
+        retention.add_policy(policy("analytics", 90, PostRetentionAction::Delete)).await.unwrap();
+        retention.add_policy(policy("backups", 30, PostRetentionAction::Review)).await.unwrap();
+
+        let audit = Arc::new(MemoryStore::new(RetentionTarget::AuditLogs));
+        audit.insert(item("a1", "audit", 400), b"login".to_vec()).await;
+        audit.insert(item("a2", "audit", 10), b"logout".to_vec()).await;
+        let analytics = Arc::new(MemoryStore::new(RetentionTarget::Analytics));
+        analytics.insert(item("e1", "analytics", 100), b"click".to_vec()).await;
+        analytics.insert(item("e2", "analytics", 120), b"open".to_vec()).await;
+        let backups = Arc::new(MemoryStore::new(RetentionTarget::Backups));
+        backups.insert(item("b1", "backups", 45), b"snapshot".to_vec()).await;
+
+        retention
+            .place_legal_hold(LegalHold {
+                id: Uuid::new_v4(),
+                name: "Case 2024-007".to_string(),
+                description: "Litigation hold".to_string(),
+                data_categories: Vec::new(),
+                data_items: HashSet::from(["e2".to_string()]),
+                started_at: Utc::now(),
+                ended_at: None,
+                custodian: "legal@company.com".to_string(),
+                case_reference: "CASE-007".to_string(),
+                active: true,
+            })
+            .await
+            .unwrap();
+
+        let cold = Arc::new(ColdStorage::default());
+        let strategy = ArchivalStrategy {
+            name: "cold".to_string(),
+            format: "raw".to_string(),
+            compression: None,
+            encrypt: false,
+            storage_location: "archive/".to_string(),
+            archive_retention: RetentionPeriod::Indefinite,
+        };
+        let engine = RetentionEngine::new()
+            .with_store(audit.clone())
+            .with_store(analytics.clone())
+            .with_store(backups.clone())
+            .with_archive(cold.clone(), strategy)
+            .with_operator("ops");
+
+        // Planning changes nothing
+        let plan = engine.plan(&retention).await.unwrap();
+        assert_eq!(plan.items.len(), 5);
+        assert_eq!(plan.with_decision(&RetentionDecision::Delete).len(), 1);
+        assert_eq!(analytics.len().await, 2);
+
+        let run = engine.execute(&retention).await.unwrap();
+        assert_eq!(run.scanned, 5);
+        assert!(run.failures.is_empty());
+        assert_eq!(run.review, vec!["backups/b1".to_string()]);
+
+        // The old audit entry moved to cold storage, the recent one stayed
+        assert_eq!(run.archived.len(), 1);
+        assert_eq!(run.archived[0].location, "archive/audit_logs/a1");
+        assert_eq!(cold.download_file("archive/audit_logs/a1").await.unwrap(), b"login");
+        assert!(!audit.contains("a1").await && audit.contains("a2").await);
+
+        // Expired analytics were purged except the one under hold
+        assert_eq!(run.deleted, 1);
+        assert_eq!(run.exempted, 1);
+        assert!(!analytics.contains("e1").await && analytics.contains("e2").await);
+        assert!(backups.contains("b1").await);
+
+        let certificate = run.certificate.unwrap();
+        assert!(certificate.verify());
+        assert_eq!(certificate.issued_by, "ops");
+        assert_eq!(certificate.items[0].id, "e1");
+        assert_eq!(certificate.items[0].sha256, hex::encode(Sha256::digest(b"click")));
+        assert_eq!(certificate.items[0].legal_basis, vec!["Records schedule 4.2".to_string()]);
+        assert_eq!(certificate.exemptions[0].id, "e2");
+        assert_eq!(engine.get_certificate(certificate.id).await.unwrap().run_id, run.id);
+
+        let mut tampered = certificate.clone();
+        tampered.items.clear();
+        assert!(!tampered.verify());
+    }
+
+    #[tokio::test]
+    async fn test_archive_without_cold_storage_keeps_data() {
+        let retention = RetentionManager::new();
+        retention.add_policy(policy("documents", 30, PostRetentionAction::Archive)).await.unwrap();
+        let documents = Arc::new(MemoryStore::new(RetentionTarget::Documents));
+        documents.insert(item("d1", "documents", 60), b"drawing".to_vec()).await;
+        documents.insert(item("d2", "documents", 90), b"drawing".to_vec()).await;
+
+        let engine = RetentionEngine::new().with_store(documents.clone()).with_batch_size(1);
+        let run = engine.execute(&retention).await.unwrap();
+
+        // One action per run, and it failed without purging anything
+        assert_eq!(run.failures.len(), 1);
+        assert!(run.certificate.is_none());
+        assert_eq!(documents.len().await, 2);
+    }
+}
@@ -0,0 +1,765 @@
+//! Policy-driven export controls
+//!
+//! An [`ExportPolicy`] limits what leaves the system: formats that may not
+//! be written at all, a watermark or stamp carrying the user and date that
+//! is forced into every export, and caps on raster resolution. Policies are
+//! assigned to tenants and roles in an [`ExportPolicySet`]; a user gets the
+//! strictest combination of every policy that applies to them.
+//!
+//! [`ExportController`] enforces the effective policy and writes every
+//! export to the audit trail with the SHA-256 of the written file. Refused
+//! exports are audited too, so attempts to take out a blocked format show
+//! up in compliance reports. Given an [`AccessHistory`], the controller
+//! also records each completed export in the document's access history.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::access::{AccessHistory, DocumentAccess, DocumentAction};
+use super::trail::{AuditEntryBuilder, AuditTrail};
+use super::{ComplianceError, ComplianceResult};
+use crate::io::document::{
+    BoundingBox, Color, Document, Entity, GeometryType, Layer, LineType, LineWeight, Text,
+    TextAlignment, Vec3,
+};
+use crate::io::dxf::{DxfVersion, DxfWriter};
+use crate::io::export::{Exporter, RasterExportSettings, RasterExporter};
+use crate::io::native::NativeFormat;
+
+/// Output formats an export policy can allow or block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ExportFormat {
+    /// CADDY native (`.cdy`, `.cdyj`)
+    Native,
+    /// AutoCAD DXF
+    Dxf,
+    /// AutoCAD DWG
+    Dwg,
+    /// Scalable Vector Graphics
+    Svg,
+    /// Portable Document Format
+    Pdf,
+    /// PNG image
+    Png,
+    /// TIFF image
+    Tiff,
+    /// JPEG image
+    Jpeg,
+    /// STEP model
+    Step,
+    /// IGES model
+    Iges,
+    /// STL mesh
+    Stl,
+    /// Wavefront OBJ mesh
+    Obj,
+    /// glTF scene
+    Gltf,
+}
+
+impl ExportFormat {
+    /// Format for a file name, by extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        Some(match ext.as_str() {
+            "cdy" | "cdyj" => Self::Native,
+            "dxf" => Self::Dxf,
+            "dwg" => Self::Dwg,
+            "svg" => Self::Svg,
+            "pdf" => Self::Pdf,
+            "png" => Self::Png,
+            "tif" | "tiff" => Self::Tiff,
+            "jpg" | "jpeg" => Self::Jpeg,
+            "step" | "stp" => Self::Step,
+            "iges" | "igs" => Self::Iges,
+            "stl" => Self::Stl,
+            "obj" => Self::Obj,
+            "gltf" | "glb" => Self::Gltf,
+            _ => return None,
+        })
+    }
+
+    /// Whether the format is a raster image
+    pub fn is_raster(self) -> bool {
+        matches!(self, Self::Png | Self::Tiff | Self::Jpeg)
+    }
+
+    /// Short name used in audit entries
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Native => "native",
+            Self::Dxf => "dxf",
+            Self::Dwg => "dwg",
+            Self::Svg => "svg",
+            Self::Pdf => "pdf",
+            Self::Png => "png",
+            Self::Tiff => "tiff",
+            Self::Jpeg => "jpeg",
+            Self::Step => "step",
+            Self::Iges => "iges",
+            Self::Stl => "stl",
+            Self::Obj => "obj",
+            Self::Gltf => "gltf",
+        }
+    }
+}
+
+/// How a watermark is laid out on the drawing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WatermarkStyle {
+    /// Large text across the middle of the drawing, along its diagonal
+    #[default]
+    Diagonal,
+    /// Small text in the lower-left corner of the drawing extents
+    Stamp,
+}
+
+/// Text forced into exported drawings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Watermark {
+    /// Text; `{user}`, `{date}`, `{tenant}` and `{format}` are filled in
+    pub text: String,
+    /// Layout on the drawing
+    pub style: WatermarkStyle,
+    /// Text height as a fraction of the smaller side of the drawing extents
+    pub height_ratio: f64,
+    /// Layer the text is placed on
+    pub layer: String,
+    /// Text color
+    pub color: Color,
+}
+
+impl Watermark {
+    /// Diagonal watermark across the drawing
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            style: WatermarkStyle::Diagonal,
+            height_ratio: 0.05,
+            layer: "WATERMARK".to_string(),
+            color: Color::new(128, 128, 128),
+        }
+    }
+
+    /// Small stamp in the corner of the drawing
+    pub fn stamp(text: &str) -> Self {
+        Self {
+            style: WatermarkStyle::Stamp,
+            height_ratio: 0.015,
+            ..Self::new(text)
+        }
+    }
+
+    /// Text for a request, with placeholders filled in
+    pub fn render(&self, request: &ExportRequest) -> String {
+        self.text
+            .replace("{user}", &request.user)
+            .replace("{date}", &request.requested_at.format("%Y-%m-%d %H:%M UTC").to_string())
+            .replace("{tenant}", &request.tenant.to_string())
+            .replace("{format}", request.format.name())
+    }
+
+    /// Add the watermark to a document, returning the text placed
+    ///
+    /// The watermark layer is forced visible, thawed and plottable, so it
+    /// cannot be suppressed by preparing a layer of the same name.
+    pub fn apply(&self, doc: &mut Document, request: &ExportRequest) -> String {
+        let text = self.render(request);
+        let extents = doc
+            .bounding_box()
+            .unwrap_or_else(|| BoundingBox::new(Vec3::zero(), Vec3::new(100.0, 100.0, 0.0)));
+        let size = extents.size();
+        let height = (size.x.min(size.y) * self.height_ratio).max(f64::EPSILON);
+
+        let (position, rotation, horizontal, vertical) = match self.style {
+            WatermarkStyle::Diagonal => (
+                extents.center(),
+                size.y.atan2(size.x),
+                TextAlignment::Center,
+                TextAlignment::Middle,
+            ),
+            WatermarkStyle::Stamp => (
+                Vec3::new(extents.min.x + height, extents.min.y + height, 0.0),
+                0.0,
+                TextAlignment::Left,
+                TextAlignment::Bottom,
+            ),
+        };
+
+        let layer = doc.layers.entry(self.layer.clone()).or_insert_with(|| Layer {
+            name: self.layer.clone(),
+            color: self.color,
+            line_type: LineType::Continuous,
+            line_weight: LineWeight::Default,
+            visible: true,
+            locked: true,
+            frozen: false,
+            plottable: true,
+            confidential: false,
+        });
+        layer.visible = true;
+        layer.frozen = false;
+        layer.plottable = true;
+        layer.confidential = false;
+
+        let mut entity = Entity::new(
+            GeometryType::Text(Text {
+                position,
+                text: text.clone(),
+                height,
+                rotation,
+                style: "Standard".to_string(),
+                horizontal_alignment: horizontal,
+                vertical_alignment: vertical,
+            }),
+            self.layer.clone(),
+        );
+        entity.color = Some(self.color);
+        doc.add_entity(entity);
+
+        text
+    }
+}
+
+/// Restrictions on exporting drawings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExportPolicy {
+    /// Policy name
+    pub name: String,
+    /// Formats that may not be exported
+    #[serde(default)]
+    pub blocked_formats: BTreeSet<ExportFormat>,
+    /// Watermark added to every export
+    #[serde(default)]
+    pub watermark: Option<Watermark>,
+    /// Highest resolution of raster exports
+    #[serde(default)]
+    pub max_raster_dpi: Option<u32>,
+    /// Most pixels (width × height) in a raster export
+    #[serde(default)]
+    pub max_raster_pixels: Option<u64>,
+}
+
+impl ExportPolicy {
+    /// Policy that allows everything
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Block a format
+    pub fn block(mut self, format: ExportFormat) -> Self {
+        self.blocked_formats.insert(format);
+        self
+    }
+
+    /// Force a watermark into every export
+    pub fn with_watermark(mut self, watermark: Watermark) -> Self {
+        self.watermark = Some(watermark);
+        self
+    }
+
+    /// Cap raster resolution
+    pub fn with_raster_limit(mut self, max_dpi: Option<u32>, max_pixels: Option<u64>) -> Self {
+        self.max_raster_dpi = max_dpi;
+        self.max_raster_pixels = max_pixels;
+        self
+    }
+
+    /// Whether a format may be exported
+    pub fn allows(&self, format: ExportFormat) -> bool {
+        !self.blocked_formats.contains(&format)
+    }
+
+    /// Fold another policy in, keeping the stricter of each restriction;
+    /// an existing watermark is kept
+    pub fn restrict(&mut self, other: &ExportPolicy) {
+        self.blocked_formats.extend(other.blocked_formats.iter().copied());
+        if self.watermark.is_none() {
+            self.watermark.clone_from(&other.watermark);
+        }
+        self.max_raster_dpi = stricter(self.max_raster_dpi, other.max_raster_dpi);
+        self.max_raster_pixels = stricter(self.max_raster_pixels, other.max_raster_pixels);
+    }
+
+    /// Bring raster settings within the policy's limits, keeping the aspect
+    /// ratio; returns whether anything was reduced
+    pub fn limit_raster(&self, settings: &mut RasterExportSettings) -> bool {
+        let mut limited = false;
+        if let Some(max_dpi) = self.max_raster_dpi {
+            if settings.dpi > max_dpi {
+                settings.dpi = max_dpi;
+                limited = true;
+            }
+        }
+        if let Some(max_pixels) = self.max_raster_pixels {
+            let pixels = u64::from(settings.width) * u64::from(settings.height);
+            if pixels > max_pixels {
+                let factor = (max_pixels as f64 / pixels as f64).sqrt();
+                settings.width = ((f64::from(settings.width) * factor).floor() as u32).max(1);
+                settings.height = ((f64::from(settings.height) * factor).floor() as u32).max(1);
+                limited = true;
+            }
+        }
+        limited
+    }
+}
+
+fn stricter<T: Ord>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// What an export policy is assigned to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PolicyScope {
+    /// Every user of a tenant (organization ID)
+    Tenant(Uuid),
+    /// Every user holding a role, in any tenant
+    Role(String),
+}
+
+/// Export policies by tenant and role
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportPolicySet {
+    policies: Vec<(PolicyScope, ExportPolicy)>,
+}
+
+impl ExportPolicySet {
+    /// Create an empty set; everything is allowed
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assign a policy, replacing any previous one for the scope
+    pub fn assign(&mut self, scope: PolicyScope, policy: ExportPolicy) {
+        self.policies.retain(|(s, _)| *s != scope);
+        self.policies.push((scope, policy));
+    }
+
+    /// Remove the policy for a scope
+    pub fn unassign(&mut self, scope: &PolicyScope) -> Option<ExportPolicy> {
+        let index = self.policies.iter().position(|(s, _)| s == scope)?;
+        Some(self.policies.remove(index).1)
+    }
+
+    /// Policy assigned to a scope
+    pub fn get(&self, scope: &PolicyScope) -> Option<&ExportPolicy> {
+        self.policies.iter().find(|(s, _)| s == scope).map(|(_, p)| p)
+    }
+
+    /// Strictest combination of the tenant's policy and those of the roles
+    ///
+    /// Role policies are folded in first, so a role's watermark takes the
+    /// place of the tenant's.
+    pub fn effective(&self, tenant: Uuid, roles: &[String]) -> ExportPolicy {
+        let mut policy = ExportPolicy::new("effective");
+        let role_policies = self.policies.iter().filter_map(|(scope, p)| match scope {
+            PolicyScope::Role(role) if roles.contains(role) => Some(p),
+            _ => None,
+        });
+        for p in role_policies.chain(self.get(&PolicyScope::Tenant(tenant))) {
+            policy.restrict(p);
+        }
+        policy
+    }
+}
+
+/// Who is exporting what
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportRequest {
+    /// User name recorded in the audit trail and watermark
+    pub user: String,
+    /// Organization the user belongs to
+    pub tenant: Uuid,
+    /// Roles held by the user
+    pub roles: Vec<String>,
+    /// Output format
+    pub format: ExportFormat,
+    /// Time of the request
+    pub requested_at: DateTime<Utc>,
+}
+
+impl ExportRequest {
+    /// Request made now
+    pub fn new(user: &str, tenant: Uuid, roles: Vec<String>, format: ExportFormat) -> Self {
+        Self {
+            user: user.to_string(),
+            tenant,
+            roles,
+            format,
+            requested_at: Utc::now(),
+        }
+    }
+}
+
+/// A document ready to be written under a policy
+#[derive(Debug, Clone)]
+pub struct PreparedExport {
+    /// Copy of the document with the watermark applied
+    pub document: Document,
+    /// Effective policy
+    pub policy: ExportPolicy,
+    /// Watermark text placed, if any
+    pub watermark: Option<String>,
+}
+
+/// An audited export
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportRecord {
+    /// Audit entry ID
+    pub audit_id: Uuid,
+    /// Format written
+    pub format: ExportFormat,
+    /// File written
+    pub path: PathBuf,
+    /// SHA-256 of the file, hex encoded
+    pub sha256: String,
+    /// File size in bytes
+    pub bytes: u64,
+    /// Watermark text placed, if any
+    pub watermark: Option<String>,
+}
+
+/// Enforces export policies and audits every export
+pub struct ExportController {
+    policies: ExportPolicySet,
+    trail: Arc<AuditTrail>,
+    access: Option<AccessHistory>,
+}
+
+impl ExportController {
+    /// Create a controller writing to an audit trail
+    pub fn new(policies: ExportPolicySet, trail: Arc<AuditTrail>) -> Self {
+        Self {
+            policies,
+            trail,
+            access: None,
+        }
+    }
+
+    /// Also record exports in a document access history
+    pub fn with_access_history(mut self, access: AccessHistory) -> Self {
+        self.access = Some(access);
+        self
+    }
+
+    /// Policies being enforced
+    pub fn policies(&self) -> &ExportPolicySet {
+        &self.policies
+    }
+
+    /// Policies being enforced, for editing
+    pub fn policies_mut(&mut self) -> &mut ExportPolicySet {
+        &mut self.policies
+    }
+
+    /// Check a request and prepare the document for writing
+    ///
+    /// A blocked format is refused with [`ComplianceError::AccessDenied`]
+    /// and the refusal is audited.
+    pub async fn prepare(
+        &self,
+        request: &ExportRequest,
+        doc: &Document,
+    ) -> ComplianceResult<PreparedExport> {
+        let policy = self.policies.effective(request.tenant, &request.roles);
+        if !policy.allows(request.format) {
+            let entry = self
+                .audit(request, doc, "export.denied")
+                .metadata("reason", "format blocked by policy");
+            self.append(entry).await?;
+            return Err(ComplianceError::AccessDenied(format!(
+                "{} export is not permitted for {}",
+                request.format.name(),
+                request.user
+            )));
+        }
+
+        let mut document = doc.clone();
+        let watermark = policy
+            .watermark
+            .as_ref()
+            .map(|w| w.apply(&mut document, request));
+        Ok(PreparedExport {
+            document,
+            policy,
+            watermark,
+        })
+    }
+
+    /// Prepare, write with `write` and audit an export
+    pub async fn export_with<F, E>(
+        &self,
+        request: &ExportRequest,
+        doc: &Document,
+        path: &Path,
+        write: F,
+    ) -> ComplianceResult<ExportRecord>
+    where
+        F: FnOnce(&PreparedExport, &Path) -> Result<(), E>,
+        E: std::fmt::Display,
+    {
+        let prepared = self.prepare(request, doc).await?;
+        write(&prepared, path).map_err(|e| ComplianceError::Other(e.to_string()))?;
+        self.record(request, doc, &prepared, path).await
+    }
+
+    /// Export with the built-in writer for the request's format
+    ///
+    /// Raster formats use `raster` reduced to the policy's limits.
+    pub async fn export(
+        &self,
+        request: &ExportRequest,
+        doc: &Document,
+        path: &Path,
+        raster: RasterExportSettings,
+    ) -> ComplianceResult<ExportRecord> {
+        self.export_with(request, doc, path, |prepared, path| {
+            let doc = &prepared.document;
+            match request.format {
+                ExportFormat::Native => NativeFormat::new().save(doc, path).map_err(|e| e.to_string()),
+                ExportFormat::Dxf => DxfWriter::new(DxfVersion::R2018)
+                    .write_file(doc, path)
+                    .map_err(|e| e.to_string()),
+                ExportFormat::Png | ExportFormat::Tiff => {
+                    let mut settings = raster;
+                    prepared.policy.limit_raster(&mut settings);
+                    let exporter = RasterExporter::new(settings);
+                    let result = if request.format == ExportFormat::Png {
+                        exporter.export_png(doc, path)
+                    } else {
+                        exporter.export_tiff(doc, path)
+                    };
+                    result.map_err(|e| e.to_string())
+                }
+                ExportFormat::Svg | ExportFormat::Pdf => {
+                    Exporter::export(doc, path).map_err(|e| e.to_string())
+                }
+                other => Err(format!("no built-in writer for {} exports", other.name())),
+            }
+        })
+        .await
+    }
+
+    /// Hash a written file and add it to the audit trail
+    async fn record(
+        &self,
+        request: &ExportRequest,
+        doc: &Document,
+        prepared: &PreparedExport,
+        path: &Path,
+    ) -> ComplianceResult<ExportRecord> {
+        let contents = std::fs::read(path).map_err(|e| ComplianceError::Other(e.to_string()))?;
+        let sha256 = hex::encode(Sha256::digest(&contents));
+
+        let mut entry = self
+            .audit(request, doc, "export.file")
+            .metadata("path", path.display().to_string())
+            .metadata("sha256", sha256.clone())
+            .metadata("bytes", contents.len().to_string());
+        if let Some(text) = &prepared.watermark {
+            entry = entry.metadata("watermark", text.clone());
+        }
+        if request.format.is_raster() {
+            if let Some(dpi) = prepared.policy.max_raster_dpi {
+                entry = entry.metadata("max_raster_dpi", dpi.to_string());
+            }
+            if let Some(pixels) = prepared.policy.max_raster_pixels {
+                entry = entry.metadata("max_raster_pixels", pixels.to_string());
+            }
+        }
+        let audit_id = self.append(entry).await?;
+
+        if let Some(access) = &self.access {
+            let event = DocumentAccess::new(doc.id, request.user.clone(), DocumentAction::Export)
+                .at(request.requested_at)
+                .detail("format", request.format.name())
+                .detail("sha256", sha256.clone())
+                .detail("audit_id", audit_id.to_string());
+            access.record(event).await;
+        }
+
+        Ok(ExportRecord {
+            audit_id,
+            format: request.format,
+            path: path.to_path_buf(),
+            sha256,
+            bytes: contents.len() as u64,
+            watermark: prepared.watermark.clone(),
+        })
+    }
+
+    /// Audit entry for a request, with the fields every export entry carries
+    fn audit(&self, request: &ExportRequest, doc: &Document, action: &str) -> AuditEntryBuilder {
+        AuditEntryBuilder::new(request.user.clone(), action, format!("document/{}", doc.id))
+            .timestamp(request.requested_at)
+            .metadata("format", request.format.name())
+            .metadata("tenant", request.tenant.to_string())
+            .metadata("roles", request.roles.join(","))
+    }
+
+    async fn append(&self, entry: AuditEntryBuilder) -> ComplianceResult<Uuid> {
+        self.trail
+            .append(entry)
+            .await
+            .map_err(ComplianceError::IntegrityViolation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::Line;
+
+    fn drawing() -> Document {
+        let mut doc = Document::new();
+        doc.add_entity(Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::new(0.0, 0.0, 0.0),
+                end: Vec3::new(200.0, 100.0, 0.0),
+            }),
+            "0".to_string(),
+        ));
+        doc
+    }
+
+    fn request(format: ExportFormat, tenant: Uuid) -> ExportRequest {
+        ExportRequest::new("alice", tenant, vec!["contractor".to_string()], format)
+    }
+
+    #[test]
+    fn test_effective_policy_is_strictest() {
+        let tenant = Uuid::new_v4();
+        let mut set = ExportPolicySet::new();
+        set.assign(
+            PolicyScope::Tenant(tenant),
+            ExportPolicy::new("tenant")
+                .block(ExportFormat::Dwg)
+                .with_watermark(Watermark::stamp("{user}"))
+                .with_raster_limit(Some(300), None),
+        );
+        set.assign(
+            PolicyScope::Role("contractor".to_string()),
+            ExportPolicy::new("contractor")
+                .block(ExportFormat::Dxf)
+                .with_watermark(Watermark::new("CONFIDENTIAL {user}"))
+                .with_raster_limit(Some(600), Some(4_000_000)),
+        );
+
+        let policy = set.effective(tenant, &["contractor".to_string()]);
+        assert!(!policy.allows(ExportFormat::Dxf) && !policy.allows(ExportFormat::Dwg));
+        assert!(policy.allows(ExportFormat::Pdf));
+        assert_eq!(policy.max_raster_dpi, Some(300));
+        assert_eq!(policy.max_raster_pixels, Some(4_000_000));
+        assert_eq!(policy.watermark.unwrap().style, WatermarkStyle::Diagonal);
+
+        // Other tenants and roles are unaffected
+        let other = set.effective(Uuid::new_v4(), &["viewer".to_string()]);
+        assert_eq!(other.blocked_formats.len(), 0);
+        assert!(other.watermark.is_none());
+
+        let mut settings = RasterExportSettings {
+            width: 4000,
+            height: 2000,
+            dpi: 1200,
+            ..RasterExportSettings::default()
+        };
+        assert!(policy.limit_raster(&mut settings));
+        assert_eq!(settings.dpi, 300);
+        assert!(u64::from(settings.width) * u64::from(settings.height) <= 4_000_000);
+        assert_eq!(settings.width, 2 * settings.height);
+    }
+
+    #[test]
+    fn test_watermark_applied_to_copy() {
+        let doc = drawing();
+        let mut req = request(ExportFormat::Pdf, Uuid::new_v4());
+        req.requested_at = DateTime::parse_from_rfc3339("2026-03-01T09:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let mut marked = doc.clone();
+        // A pre-existing frozen layer cannot hide the watermark
+        let mut frozen = doc.get_layer("0").unwrap().clone();
+        frozen.name = "WATERMARK".to_string();
+        frozen.frozen = true;
+        frozen.plottable = false;
+        marked.add_layer(frozen);
+
+        let text = Watermark::new("COPY {user} {date} {format}").apply(&mut marked, &req);
+        assert_eq!(text, "COPY alice 2026-03-01 09:30 UTC pdf");
+        let layer = marked.get_layer("WATERMARK").unwrap();
+        assert!(!layer.frozen && layer.plottable);
+
+        let placed = marked.entities_on_layer("WATERMARK");
+        assert_eq!(placed.len(), 1);
+        match &placed[0].geometry {
+            GeometryType::Text(t) => {
+                assert!((t.position.x - 100.0).abs() < 1e-9 && (t.position.y - 50.0).abs() < 1e-9);
+                assert!((t.height - 5.0).abs() < 1e-9);
+                assert!((t.rotation - 0.5f64.atan()).abs() < 1e-9);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exports_are_audited_with_hash() {
+        let tenant = Uuid::new_v4();
+        let mut set = ExportPolicySet::new();
+        set.assign(
+            PolicyScope::Tenant(tenant),
+            ExportPolicy::new("external")
+                .block(ExportFormat::Dxf)
+                .with_watermark(Watermark::stamp("{user}")),
+        );
+        let trail = Arc::new(AuditTrail::new());
+        let access = AccessHistory::new();
+        let controller = ExportController::new(set, trail.clone()).with_access_history(access.clone());
+        let doc = drawing();
+
+        let denied = controller
+            .prepare(&request(ExportFormat::Dxf, tenant), &doc)
+            .await;
+        assert!(matches!(denied, Err(ComplianceError::AccessDenied(_))));
+
+        let path = std::env::temp_dir().join(format!("caddy_export_{}.svg", Uuid::new_v4()));
+        let record = controller
+            .export_with(&request(ExportFormat::Svg, tenant), &doc, &path, |prepared, path| {
+                assert_eq!(prepared.document.entities.len(), 2);
+                std::fs::write(path, b"<svg/>")
+            })
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(record.sha256, hex::encode(Sha256::digest(b"<svg/>")));
+        assert_eq!(record.watermark.as_deref(), Some("alice"));
+        // The caller's document is untouched
+        assert_eq!(doc.entities.len(), 1);
+
+        let entries = trail.get_entries_by_actor("alice").await;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].action, "export.denied");
+        assert_eq!(entries[0].metadata["format"], "dxf");
+        assert_eq!(entries[1].action, "export.file");
+        assert_eq!(entries[1].metadata["sha256"], record.sha256);
+        assert_eq!(entries[1].resource, format!("document/{}", doc.id));
+        assert!(trail.verify_chain().await.is_ok());
+
+        // Only the completed export reaches the access history
+        let history = access.history(doc.id, &Default::default()).await;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].action, DocumentAction::Export);
+        assert_eq!(history[0].details["sha256"], record.sha256);
+    }
+}
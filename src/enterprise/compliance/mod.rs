@@ -12,8 +12,11 @@
 //! - **SOC 2 Controls**: Trust service criteria implementation and evidence collection
 //! - **HIPAA Compliance**: PHI access logging and breach notification tracking
 //! - **Retention Policies**: Configurable data lifecycle and legal hold management
+//! - **Retention Enforcement**: Archival, legal-hold-aware purges and deletion certificates
 //! - **Compliance Reporting**: Automated report generation in multiple formats
 //! - **Alert System**: Real-time compliance violation detection and escalation
+//! - **Export Controls**: Per-tenant/role format blocking, watermarks and raster limits
+//! - **Access History**: Per-document view, edit, export and share logging
 //!
 //! ## Architecture
 //!
@@ -27,8 +30,11 @@
 //! ├── soc2.rs           - SOC 2 controls and evidence
 //! ├── hipaa.rs          - HIPAA compliance and PHI protection
 //! ├── retention.rs      - Data retention policies
+//! ├── enforcement.rs    - Retention execution engine and deletion certificates
 //! ├── reporting.rs      - Compliance report generation
 //! ├── alerts.rs         - Alert rules and anomaly detection
+//! ├── export.rs         - Export policies, watermarks and export auditing
+//! ├── access.rs         - Per-document access history
 //! └── mod.rs            - Module exports
 //! ```
 //!
@@ -184,6 +190,13 @@ pub mod hipaa;
 /// and automated purge scheduling.
 pub mod retention;
 
+/// Retention enforcement
+///
+/// Scans audit logs, documents, analytics and backups against retention
+/// policies, archives to cold storage, purges with legal-hold exemptions,
+/// and issues deletion certificates.
+pub mod enforcement;
+
 /// Compliance reporting and evidence collection
 ///
 /// Report generation, evidence aggregation, multi-format export
@@ -196,6 +209,18 @@ pub mod reporting;
 /// and multi-channel notifications.
 pub mod alerts;
 
+/// Export controls and watermarking
+///
+/// Per-tenant and per-role export policies: blocked formats, forced
+/// watermarks, raster resolution caps, and audited exports with file hashes.
+pub mod export;
+
+/// Per-document access history
+///
+/// Who viewed, edited, exported or shared each document, kept for as long
+/// as the retention policy for access history allows.
+pub mod access;
+
 // ============================================================================
 // Re-exports for Convenience
 // ============================================================================
@@ -234,6 +259,13 @@ pub use retention::{
     RetentionManager, RetentionPeriod, RetentionPolicy, RetentionRecord,
 };
 
+// Retention enforcement
+pub use enforcement::{
+    ArchivedItem, DeletedItem, DeletionCertificate, Exemption, MemoryStore, PlannedItem,
+    RetainedItem, RetentionDecision, RetentionEngine, RetentionFailure, RetentionPlan,
+    RetentionRun, RetentionStore, RetentionTarget,
+};
+
 // Reporting
 pub use reporting::{
     ComplianceReport, EvidenceReference, ReportBuilder, ReportFormat,
@@ -246,6 +278,18 @@ pub use alerts::{
     ComplianceAlert, EscalationPolicy, NotificationChannel, RuleCondition,
 };
 
+// Export controls
+pub use export::{
+    ExportController, ExportFormat, ExportPolicy, ExportPolicySet, ExportRecord,
+    ExportRequest, PolicyScope, PreparedExport, Watermark, WatermarkStyle,
+};
+
+// Access history
+pub use access::{
+    AccessHistory, AccessQuery, DocumentAccess, DocumentAccessSummary, DocumentAction,
+    ACCESS_HISTORY_CATEGORY,
+};
+
 // ============================================================================
 // Common Types
 // ============================================================================
@@ -370,12 +414,18 @@ pub struct ComplianceManager {
     /// Retention manager
     pub retention: retention::RetentionManager,
 
+    /// Retention enforcement engine
+    pub enforcement: enforcement::RetentionEngine,
+
     /// Reporting manager
     pub reporting: reporting::ReportingManager,
 
     /// Alert manager
     pub alerts: alerts::AlertManager,
 
+    /// Per-document access history
+    pub access: access::AccessHistory,
+
     /// Configuration
     config: ComplianceConfig,
 }
@@ -449,8 +499,10 @@ impl ComplianceManager {
             soc2: soc2::Soc2Manager::new(),
             hipaa: hipaa::HipaaManager::new(),
             retention: retention::RetentionManager::new(),
+            enforcement: enforcement::RetentionEngine::new(),
             reporting: reporting::ReportingManager::new(),
             alerts: alerts::AlertManager::new(),
+            access: access::AccessHistory::new(),
             config,
         }
     }
@@ -504,6 +556,10 @@ impl ComplianceManager {
             content["frameworks"]["hipaa"] = serde_json::json!(hipaa_report);
         }
 
+        if self.config.enable_audit_trail {
+            content["document_access"] = self.access.generate_report(start, end).await;
+        }
+
         // Generate report
         let builder = reporting::ReportBuilder::new(
             reporting::ReportType::ComplianceAttestation,
@@ -518,6 +574,49 @@ impl ComplianceManager {
             .await
             .map_err(|e| ComplianceError::ReportingError(e))
     }
+
+    /// Drop access history the retention policy has expired
+    ///
+    /// Returns the number of access events removed. Does nothing when
+    /// retention is disabled.
+    pub async fn purge_access_history(&self) -> usize {
+        if !self.config.enable_retention {
+            return 0;
+        }
+        self.access.apply_retention(&self.retention).await
+    }
+
+    /// Enforce retention policies across every registered store
+    ///
+    /// Also purges expired access history, and records the run in the
+    /// audit trail when the trail is enabled.
+    pub async fn enforce_retention(&self) -> ComplianceResult<enforcement::RetentionRun> {
+        if !self.config.enable_retention {
+            return Err(ComplianceError::InvalidOperation("Retention is disabled".to_string()));
+        }
+
+        let run = self.enforcement.execute(&self.retention).await?;
+        let access_events = self.purge_access_history().await;
+
+        if self.config.enable_audit_trail {
+            let resource = format!("retention_run/{}", run.id);
+            let mut entry = trail::AuditEntryBuilder::new("retention_engine", "retention.execute", resource)
+                .metadata("scanned", run.scanned.to_string())
+                .metadata("archived", run.archived.len().to_string())
+                .metadata("deleted", run.deleted.to_string())
+                .metadata("exempted", run.exempted.to_string())
+                .metadata("failures", run.failures.len().to_string())
+                .metadata("access_events_purged", access_events.to_string());
+            if let Some(certificate) = &run.certificate {
+                entry = entry
+                    .metadata("certificate_id", certificate.id.to_string())
+                    .metadata("certificate_digest", certificate.digest.clone());
+            }
+            self.trail.append(entry).await.map_err(ComplianceError::Other)?;
+        }
+
+        Ok(run)
+    }
 }
 
 impl Default for ComplianceManager {
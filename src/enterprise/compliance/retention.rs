@@ -387,6 +387,16 @@ impl RetentionManager {
         holds.values().any(|h| h.applies_to(data_id, category))
     }
 
+    /// IDs of the active holds covering a data item
+    pub async fn holds_for(&self, data_id: &str, category: &str) -> Vec<Uuid> {
+        let holds = self.legal_holds.read().await;
+        holds
+            .values()
+            .filter(|h| h.applies_to(data_id, category))
+            .map(|h| h.id)
+            .collect()
+    }
+
     // ========================================================================
     // Record Management
     // ========================================================================
@@ -507,6 +517,17 @@ impl RetentionManager {
         }
     }
 
+    /// Mark record as deleted after its data was purged elsewhere
+    pub async fn mark_deleted(&self, data_id: &str) -> Result<(), String> {
+        let mut records = self.records.write().await;
+        if let Some(record) = records.get_mut(data_id) {
+            record.lifecycle_stage = LifecycleStage::Deleted;
+            Ok(())
+        } else {
+            Err("Record not found".to_string())
+        }
+    }
+
     // ========================================================================
     // Archival
     // ========================================================================
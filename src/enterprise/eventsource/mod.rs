@@ -99,6 +99,14 @@
 //! - Timeout handling
 //! - Process persistence
 //!
+//! ### Schema Registry
+//!
+//! Contracts for outbound payloads with:
+//! - Versioned JSON Schemas per event type
+//! - Compatibility checks on schema evolution
+//! - Validation before events are stored or delivered
+//! - Rejection counts per event type
+//!
 //! ## Quick Start
 //!
 //! ### 1. Define Events
@@ -396,6 +404,7 @@ pub mod command;
 pub mod projection;
 pub mod replay;
 pub mod saga;
+pub mod schema;
 pub mod snapshot;
 pub mod store;
 
@@ -413,6 +422,10 @@ pub use replay::{
 pub use saga::{
     InMemorySagaStore, Saga, SagaCoordinator, SagaInstance, SagaStatus, SagaStep, SagaStore,
 };
+pub use schema::{
+    Compatibility, JsonSchema, SchemaError, SchemaRegistry, SchemaStats, SchemaVersion,
+    SchemaViolation, ValidatingEventStore,
+};
 pub use snapshot::{
     AlwaysSnapshotPolicy, EveryNEventsPolicy, InMemorySnapshotStore, NoSnapshotPolicy, Snapshot,
     SnapshotPolicy, SnapshotRepository, SnapshotStore,
@@ -0,0 +1,1124 @@
+//! Schema Registry
+//!
+//! Versioned JSON Schemas for outbound event payloads, so integrators can
+//! code against a stable contract. Each subject (an event type such as
+//! `Drawing.Created` or `webhook.issue_detected`) holds a list of schema
+//! versions; a new version is only accepted if it is compatible with the
+//! latest one under the subject's [`Compatibility`] mode. Payloads are
+//! validated against the latest version before they leave the system, and
+//! rejections are counted per subject.
+//!
+//! The supported JSON Schema subset is `type`, `enum`, `const`,
+//! `properties`, `required`, `additionalProperties`, `items`, `minItems`,
+//! `maxItems`, `minLength`, `maxLength`, `pattern`, `minimum`, `maximum`,
+//! `exclusiveMinimum`, `exclusiveMaximum`, `allOf`, `anyOf`, `oneOf` and
+//! `not`, plus boolean schemas. `$ref` is rejected; other keywords are
+//! treated as annotations.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use thiserror::Error;
+
+use super::store::{EventData, EventStore, StoredEvent, StreamSlice};
+use crate::enterprise::error::{EnterpriseError, EnterpriseResult};
+use crate::enterprise::tracing::metrics::{Counter, MetricRegistry};
+
+/// How a new schema version must relate to the latest one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compatibility {
+    /// Any change is accepted
+    None,
+    /// Payloads valid under the old schema stay valid under the new one
+    Backward,
+    /// Payloads valid under the new schema are valid under the old one, so
+    /// consumers still on the old contract keep working
+    Forward,
+    /// Both backward and forward
+    #[default]
+    Full,
+}
+
+/// One registered schema version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaVersion {
+    /// Subject the schema belongs to
+    pub subject: String,
+    /// Version number, starting at 1
+    pub version: u32,
+    /// The JSON Schema document
+    pub schema: Value,
+    /// Registration time
+    pub registered_at: DateTime<Utc>,
+}
+
+/// Validation counts for a subject
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SchemaStats {
+    /// Payloads validated
+    pub validated: u64,
+    /// Payloads rejected
+    pub rejected: u64,
+    /// First violation of the most recent rejection
+    pub last_rejection: Option<String>,
+    /// Time of the most recent rejection
+    pub last_rejected_at: Option<DateTime<Utc>>,
+}
+
+/// A payload's departure from its schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaViolation {
+    /// JSON pointer to the offending value (`""` for the root)
+    pub pointer: String,
+    /// What is wrong
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.pointer, self.message)
+        }
+    }
+}
+
+/// Schema registry errors
+#[derive(Error, Debug)]
+pub enum SchemaError {
+    /// Schema document is malformed or uses unsupported keywords
+    #[error("Invalid schema: {0}")]
+    InvalidSchema(String),
+
+    /// New version breaks the subject's compatibility mode
+    #[error("Schema for {subject} is not {mode:?} compatible with version {version}: {}", .issues.join("; "))]
+    Incompatible {
+        subject: String,
+        version: u32,
+        mode: Compatibility,
+        issues: Vec<String>,
+    },
+
+    /// No such subject or version
+    #[error("Schema not found: {0}")]
+    NotFound(String),
+
+    /// Payload failed validation
+    #[error("Payload rejected by {subject} v{version}: {}", .violations.first().map(|v| v.to_string()).unwrap_or_default())]
+    Rejected {
+        subject: String,
+        version: u32,
+        violations: Vec<SchemaViolation>,
+    },
+
+    /// Payload is not JSON
+    #[error("Payload for {subject} is not JSON: {reason}")]
+    NotJson { subject: String, reason: String },
+}
+
+impl From<SchemaError> for EnterpriseError {
+    fn from(err: SchemaError) -> Self {
+        EnterpriseError::Other(err.to_string())
+    }
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+struct Subject {
+    compatibility: Compatibility,
+    versions: Vec<(SchemaVersion, Arc<JsonSchema>)>,
+    stats: SchemaStats,
+}
+
+impl Subject {
+    fn new(compatibility: Compatibility) -> Self {
+        Self {
+            compatibility,
+            versions: Vec::new(),
+            stats: SchemaStats::default(),
+        }
+    }
+}
+
+/// Registry of versioned payload schemas
+pub struct SchemaRegistry {
+    subjects: RwLock<HashMap<String, Subject>>,
+    default_compatibility: Compatibility,
+    validated_total: Option<Counter>,
+    rejected_total: Option<Counter>,
+}
+
+impl SchemaRegistry {
+    /// Create an empty registry; subjects default to [`Compatibility::Full`]
+    pub fn new() -> Self {
+        Self {
+            subjects: RwLock::new(HashMap::new()),
+            default_compatibility: Compatibility::default(),
+            validated_total: None,
+            rejected_total: None,
+        }
+    }
+
+    /// Compatibility mode for subjects without their own
+    pub fn with_default_compatibility(mut self, compatibility: Compatibility) -> Self {
+        self.default_compatibility = compatibility;
+        self
+    }
+
+    /// Also count validations and rejections in `metrics`
+    pub fn with_metrics(mut self, metrics: &MetricRegistry) -> Self {
+        self.validated_total = Some(metrics.counter(
+            "schema_validations_total",
+            "Event payloads validated against a registered schema",
+        ));
+        self.rejected_total = Some(metrics.counter(
+            "schema_rejections_total",
+            "Event payloads rejected by their registered schema",
+        ));
+        self
+    }
+
+    /// Set the compatibility mode of `subject`
+    pub fn set_compatibility(&self, subject: &str, compatibility: Compatibility) {
+        self.subjects
+            .write()
+            .entry(subject.to_string())
+            .or_insert_with(|| Subject::new(compatibility))
+            .compatibility = compatibility;
+    }
+
+    /// Compatibility mode of `subject`
+    pub fn compatibility(&self, subject: &str) -> Compatibility {
+        self.subjects
+            .read()
+            .get(subject)
+            .map_or(self.default_compatibility, |s| s.compatibility)
+    }
+
+    /// Register `schema` as the next version of `subject`
+    ///
+    /// Registering the latest schema again returns its existing version.
+    /// Compatibility is checked against the latest version only.
+    pub fn register(&self, subject: &str, schema: Value) -> Result<SchemaVersion, SchemaError> {
+        let compiled = JsonSchema::compile(&schema)?;
+        let mut subjects = self.subjects.write();
+        let entry = subjects
+            .entry(subject.to_string())
+            .or_insert_with(|| Subject::new(self.default_compatibility));
+
+        if let Some((latest, latest_compiled)) = entry.versions.last() {
+            if latest.schema == schema {
+                return Ok(latest.clone());
+            }
+            let issues = compatibility_issues(entry.compatibility, latest_compiled, &compiled);
+            if !issues.is_empty() {
+                return Err(SchemaError::Incompatible {
+                    subject: subject.to_string(),
+                    version: latest.version,
+                    mode: entry.compatibility,
+                    issues,
+                });
+            }
+        }
+
+        let version = SchemaVersion {
+            subject: subject.to_string(),
+            version: entry.versions.len() as u32 + 1,
+            schema,
+            registered_at: Utc::now(),
+        };
+        entry.versions.push((version.clone(), Arc::new(compiled)));
+        Ok(version)
+    }
+
+    /// Problems that would stop `schema` from being registered under
+    /// `subject`; empty if it is compatible
+    pub fn check_compatibility(&self, subject: &str, schema: &Value) -> Result<Vec<String>, SchemaError> {
+        let compiled = JsonSchema::compile(schema)?;
+        let subjects = self.subjects.read();
+        let Some(entry) = subjects.get(subject) else {
+            return Ok(Vec::new());
+        };
+        Ok(entry
+            .versions
+            .last()
+            .map(|(_, latest)| compatibility_issues(entry.compatibility, latest, &compiled))
+            .unwrap_or_default())
+    }
+
+    /// Subjects with at least one schema, sorted
+    pub fn subjects(&self) -> Vec<String> {
+        let mut subjects: Vec<String> = self
+            .subjects
+            .read()
+            .iter()
+            .filter(|(_, s)| !s.versions.is_empty())
+            .map(|(name, _)| name.clone())
+            .collect();
+        subjects.sort();
+        subjects
+    }
+
+    /// Latest schema of `subject`
+    pub fn latest(&self, subject: &str) -> Option<SchemaVersion> {
+        self.subjects
+            .read()
+            .get(subject)?
+            .versions
+            .last()
+            .map(|(version, _)| version.clone())
+    }
+
+    /// A specific schema version of `subject`
+    pub fn version(&self, subject: &str, version: u32) -> Option<SchemaVersion> {
+        let index = (version as usize).checked_sub(1)?;
+        self.subjects
+            .read()
+            .get(subject)?
+            .versions
+            .get(index)
+            .map(|(version, _)| version.clone())
+    }
+
+    /// Validation counts of `subject`
+    pub fn stats(&self, subject: &str) -> Option<SchemaStats> {
+        self.subjects.read().get(subject).map(|s| s.stats.clone())
+    }
+
+    /// Validate `payload` against the latest schema of `subject`
+    ///
+    /// Payloads of subjects without a schema pass unchecked and uncounted.
+    pub fn validate(&self, subject: &str, payload: &Value) -> Result<(), SchemaError> {
+        let latest = self
+            .subjects
+            .read()
+            .get(subject)
+            .and_then(|s| s.versions.last())
+            .map(|(version, compiled)| (version.version, compiled.clone()));
+        let Some((version, schema)) = latest else {
+            return Ok(());
+        };
+
+        let violations = schema.validate(payload);
+        let rejected = !violations.is_empty();
+        if let Some(stats) = self.subjects.write().get_mut(subject).map(|s| &mut s.stats) {
+            stats.validated += 1;
+            if rejected {
+                stats.rejected += 1;
+                stats.last_rejection = Some(violations[0].to_string());
+                stats.last_rejected_at = Some(Utc::now());
+            }
+        }
+        if let Some(counter) = &self.validated_total {
+            counter.inc();
+        }
+
+        if rejected {
+            if let Some(counter) = &self.rejected_total {
+                counter.inc();
+            }
+            return Err(SchemaError::Rejected {
+                subject: subject.to_string(),
+                version,
+                violations,
+            });
+        }
+        Ok(())
+    }
+
+    /// Validate a serialized JSON payload
+    pub fn validate_bytes(&self, subject: &str, payload: &[u8]) -> Result<(), SchemaError> {
+        if self.latest(subject).is_none() {
+            return Ok(());
+        }
+        let value: Value = serde_json::from_slice(payload).map_err(|err| SchemaError::NotJson {
+            subject: subject.to_string(),
+            reason: err.to_string(),
+        })?;
+        self.validate(subject, &value)
+    }
+}
+
+impl Default for SchemaRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// Validating Event Store
+// ============================================================================
+
+/// Event store that validates each event against the schema registered
+/// for its event type before appending
+///
+/// A batch with any invalid event is rejected as a whole.
+pub struct ValidatingEventStore<S> {
+    inner: S,
+    registry: Arc<SchemaRegistry>,
+}
+
+impl<S: EventStore> ValidatingEventStore<S> {
+    /// Wrap `inner`
+    pub fn new(inner: S, registry: Arc<SchemaRegistry>) -> Self {
+        Self { inner, registry }
+    }
+
+    /// Registry used for validation
+    pub fn registry(&self) -> &Arc<SchemaRegistry> {
+        &self.registry
+    }
+}
+
+#[async_trait]
+impl<S: EventStore> EventStore for ValidatingEventStore<S> {
+    async fn append_events(&self, events: Vec<EventData>) -> EnterpriseResult<Vec<StoredEvent>> {
+        for event in &events {
+            self.registry.validate_bytes(&event.event_type, &event.data)?;
+        }
+        self.inner.append_events(events).await
+    }
+
+    async fn read_stream(
+        &self,
+        stream_id: &str,
+        from_version: u64,
+        max_count: usize,
+    ) -> EnterpriseResult<StreamSlice> {
+        self.inner.read_stream(stream_id, from_version, max_count).await
+    }
+
+    async fn read_all(&self, from_sequence: u64, max_count: usize) -> EnterpriseResult<Vec<StoredEvent>> {
+        self.inner.read_all(from_sequence, max_count).await
+    }
+
+    async fn get_stream_version(&self, stream_id: &str) -> EnterpriseResult<u64> {
+        self.inner.get_stream_version(stream_id).await
+    }
+
+    async fn stream_exists(&self, stream_id: &str) -> EnterpriseResult<bool> {
+        self.inner.stream_exists(stream_id).await
+    }
+
+    async fn delete_stream(&self, stream_id: &str) -> EnterpriseResult<()> {
+        self.inner.delete_stream(stream_id).await
+    }
+
+    async fn get_global_sequence(&self) -> EnterpriseResult<u64> {
+        self.inner.get_global_sequence().await
+    }
+}
+
+// ============================================================================
+// Compiled Schema
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JsonType {
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl JsonType {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "null" => Self::Null,
+            "boolean" => Self::Boolean,
+            "integer" => Self::Integer,
+            "number" => Self::Number,
+            "string" => Self::String,
+            "array" => Self::Array,
+            "object" => Self::Object,
+            _ => return None,
+        })
+    }
+
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            Self::Null => value.is_null(),
+            Self::Boolean => value.is_boolean(),
+            Self::Integer => {
+                value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+            }
+            Self::Number => value.is_number(),
+            Self::String => value.is_string(),
+            Self::Array => value.is_array(),
+            Self::Object => value.is_object(),
+        }
+    }
+
+    /// Every value of `self` is also of `other`
+    fn within(self, other: Self) -> bool {
+        self == other || (self == Self::Integer && other == Self::Number)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bound {
+    value: f64,
+    exclusive: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+enum Additional {
+    #[default]
+    Allowed,
+    Forbidden,
+    Schema(Box<Node>),
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    source: Value,
+    /// `false` schema
+    never: bool,
+    types: Option<Vec<JsonType>>,
+    allowed: Option<Vec<Value>>,
+    properties: BTreeMap<String, Node>,
+    required: Vec<String>,
+    additional: Additional,
+    items: Option<Box<Node>>,
+    min_items: Option<usize>,
+    max_items: Option<usize>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    pattern: Option<Regex>,
+    minimum: Option<Bound>,
+    maximum: Option<Bound>,
+    all_of: Vec<Node>,
+    any_of: Vec<Node>,
+    one_of: Vec<Node>,
+    not: Option<Box<Node>>,
+}
+
+/// Compiled JSON Schema
+#[derive(Debug, Clone)]
+pub struct JsonSchema {
+    root: Node,
+}
+
+impl JsonSchema {
+    /// Compile a schema document
+    pub fn compile(schema: &Value) -> Result<Self, SchemaError> {
+        Ok(Self {
+            root: compile_node(schema, "#")?,
+        })
+    }
+
+    /// Violations of `value`; empty if it is valid
+    pub fn validate(&self, value: &Value) -> Vec<SchemaViolation> {
+        let mut violations = Vec::new();
+        validate_node(&self.root, value, "", &mut violations);
+        violations
+    }
+
+    /// Whether `value` is valid
+    pub fn is_valid(&self, value: &Value) -> bool {
+        self.validate(value).is_empty()
+    }
+}
+
+fn compile_node(schema: &Value, at: &str) -> Result<Node, SchemaError> {
+    let invalid = |reason: String| SchemaError::InvalidSchema(format!("{}: {}", at, reason));
+    let object = match schema {
+        Value::Bool(true) => return Ok(Node::default()),
+        Value::Bool(false) => {
+            return Ok(Node {
+                source: schema.clone(),
+                never: true,
+                ..Node::default()
+            })
+        }
+        Value::Object(object) => object,
+        _ => return Err(invalid("schema must be an object or boolean".to_string())),
+    };
+    if object.contains_key("$ref") {
+        return Err(invalid("$ref is not supported".to_string()));
+    }
+
+    let size = |key: &str| -> Result<Option<usize>, SchemaError> {
+        object
+            .get(key)
+            .map(|v| {
+                v.as_u64()
+                    .map(|n| n as usize)
+                    .ok_or_else(|| invalid(format!("{} must be a non-negative integer", key)))
+            })
+            .transpose()
+    };
+    let list = |key: &str| -> Result<Vec<Node>, SchemaError> {
+        match object.get(key) {
+            None => Ok(Vec::new()),
+            Some(Value::Array(items)) if !items.is_empty() => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| compile_node(item, &format!("{}/{}/{}", at, key, i)))
+                .collect(),
+            Some(_) => Err(invalid(format!("{} must be a non-empty array", key))),
+        }
+    };
+
+    let types = match object.get("type") {
+        None => None,
+        Some(Value::String(name)) => Some(vec![
+            JsonType::parse(name).ok_or_else(|| invalid(format!("unknown type {:?}", name)))?,
+        ]),
+        Some(Value::Array(names)) => Some(
+            names
+                .iter()
+                .map(|name| {
+                    name.as_str()
+                        .and_then(JsonType::parse)
+                        .ok_or_else(|| invalid(format!("unknown type {}", name)))
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        Some(_) => return Err(invalid("type must be a string or array".to_string())),
+    };
+
+    let allowed = match (object.get("enum"), object.get("const")) {
+        (Some(Value::Array(values)), None) => Some(values.clone()),
+        (None, Some(value)) => Some(vec![value.clone()]),
+        (None, None) => None,
+        (Some(Value::Array(_)), Some(_)) => return Err(invalid("use either enum or const".to_string())),
+        (Some(_), _) => return Err(invalid("enum must be an array".to_string())),
+    };
+
+    let mut properties = BTreeMap::new();
+    if let Some(props) = object.get("properties") {
+        let props = props
+            .as_object()
+            .ok_or_else(|| invalid("properties must be an object".to_string()))?;
+        for (name, prop) in props {
+            properties.insert(name.clone(), compile_node(prop, &format!("{}/properties/{}", at, name))?);
+        }
+    }
+
+    let required = match object.get("required") {
+        None => Vec::new(),
+        Some(Value::Array(names)) => names
+            .iter()
+            .map(|name| {
+                name.as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| invalid("required must list property names".to_string()))
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err(invalid("required must be an array".to_string())),
+    };
+
+    let additional = match object.get("additionalProperties") {
+        None | Some(Value::Bool(true)) => Additional::Allowed,
+        Some(Value::Bool(false)) => Additional::Forbidden,
+        Some(schema) => Additional::Schema(Box::new(compile_node(
+            schema,
+            &format!("{}/additionalProperties", at),
+        )?)),
+    };
+
+    let items = object
+        .get("items")
+        .map(|items| compile_node(items, &format!("{}/items", at)).map(Box::new))
+        .transpose()?;
+
+    let pattern = object
+        .get("pattern")
+        .map(|pattern| {
+            pattern
+                .as_str()
+                .ok_or_else(|| invalid("pattern must be a string".to_string()))
+                .and_then(|p| Regex::new(p).map_err(|err| invalid(format!("bad pattern: {}", err))))
+        })
+        .transpose()?;
+
+    let minimum = bound(object, "minimum", "exclusiveMinimum", true).map_err(invalid)?;
+    let maximum = bound(object, "maximum", "exclusiveMaximum", false).map_err(invalid)?;
+
+    Ok(Node {
+        source: schema.clone(),
+        never: false,
+        types,
+        allowed,
+        properties,
+        required,
+        additional,
+        items,
+        min_items: size("minItems")?,
+        max_items: size("maxItems")?,
+        min_length: size("minLength")?,
+        max_length: size("maxLength")?,
+        pattern,
+        minimum,
+        maximum,
+        all_of: list("allOf")?,
+        any_of: list("anyOf")?,
+        one_of: list("oneOf")?,
+        not: object
+            .get("not")
+            .map(|not| compile_node(not, &format!("{}/not", at)).map(Box::new))
+            .transpose()?,
+    })
+}
+
+/// `minimum`/`exclusiveMinimum` (or the maximum pair) as one bound, the
+/// tighter one when both are given. Draft 4 spelled exclusivity as a
+/// boolean beside the inclusive keyword.
+fn bound(
+    object: &serde_json::Map<String, Value>,
+    inclusive: &str,
+    exclusive: &str,
+    lower: bool,
+) -> Result<Option<Bound>, String> {
+    let number = |key: &str| {
+        object
+            .get(key)
+            .map(|v| v.as_f64().ok_or_else(|| format!("{} must be a number", key)))
+            .transpose()
+    };
+    let base = number(inclusive)?.map(|value| Bound { value, exclusive: false });
+    match object.get(exclusive) {
+        None => Ok(base),
+        Some(Value::Bool(flag)) => Ok(base.map(|b| Bound { exclusive: *flag, ..b })),
+        Some(_) => {
+            let strict = number(exclusive)?.map(|value| Bound { value, exclusive: true });
+            Ok(match (base, strict) {
+                (Some(a), Some(b)) => Some(if bound_within(b, a, lower) { b } else { a }),
+                (a, b) => a.or(b),
+            })
+        }
+    }
+}
+
+/// Every value bound `a` admits is admitted by `b`
+fn bound_within(a: Bound, b: Bound, lower: bool) -> bool {
+    let stricter = if lower { a.value > b.value } else { a.value < b.value };
+    stricter || (a.value == b.value && (a.exclusive || !b.exclusive))
+}
+
+fn validate_node(node: &Node, value: &Value, pointer: &str, out: &mut Vec<SchemaViolation>) {
+    let mut fail = |message: String| {
+        out.push(SchemaViolation {
+            pointer: pointer.to_string(),
+            message,
+        })
+    };
+    if node.never {
+        fail("no value is allowed here".to_string());
+        return;
+    }
+    if let Some(types) = &node.types {
+        if !types.iter().any(|t| t.matches(value)) {
+            let names: Vec<String> = types.iter().map(|t| format!("{:?}", t).to_lowercase()).collect();
+            fail(format!("expected {}, got {}", names.join(" or "), type_name(value)));
+            return;
+        }
+    }
+    if let Some(allowed) = &node.allowed {
+        if !allowed.contains(value) {
+            fail(format!("{} is not one of the allowed values", value));
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            for name in &node.required {
+                if !object.contains_key(name) {
+                    fail(format!("missing required property {:?}", name));
+                }
+            }
+            for (name, item) in object {
+                let child = format!("{}/{}", pointer, escape_pointer(name));
+                match (node.properties.get(name), &node.additional) {
+                    (Some(schema), _) => validate_node(schema, item, &child, out),
+                    (None, Additional::Allowed) => {}
+                    (None, Additional::Forbidden) => out.push(SchemaViolation {
+                        pointer: child,
+                        message: "property is not allowed".to_string(),
+                    }),
+                    (None, Additional::Schema(schema)) => validate_node(schema, item, &child, out),
+                }
+            }
+        }
+        Value::Array(items) => {
+            if node.min_items.is_some_and(|min| items.len() < min) {
+                fail(format!("expected at least {} items", node.min_items.unwrap_or_default()));
+            }
+            if node.max_items.is_some_and(|max| items.len() > max) {
+                fail(format!("expected at most {} items", node.max_items.unwrap_or_default()));
+            }
+            if let Some(schema) = &node.items {
+                for (i, item) in items.iter().enumerate() {
+                    validate_node(schema, item, &format!("{}/{}", pointer, i), out);
+                }
+            }
+        }
+        Value::String(text) => {
+            let length = text.chars().count();
+            if node.min_length.is_some_and(|min| length < min) {
+                fail(format!("shorter than {} characters", node.min_length.unwrap_or_default()));
+            }
+            if node.max_length.is_some_and(|max| length > max) {
+                fail(format!("longer than {} characters", node.max_length.unwrap_or_default()));
+            }
+            if let Some(pattern) = &node.pattern {
+                if !pattern.is_match(text) {
+                    fail(format!("does not match pattern {:?}", pattern.as_str()));
+                }
+            }
+        }
+        Value::Number(number) => {
+            let n = number.as_f64().unwrap_or(f64::NAN);
+            if let Some(min) = node.minimum {
+                if n < min.value || (min.exclusive && n == min.value) {
+                    fail(format!("{} is below the minimum {}", n, min.value));
+                }
+            }
+            if let Some(max) = node.maximum {
+                if n > max.value || (max.exclusive && n == max.value) {
+                    fail(format!("{} is above the maximum {}", n, max.value));
+                }
+            }
+        }
+        _ => {}
+    }
+
+    for schema in &node.all_of {
+        validate_node(schema, value, pointer, out);
+    }
+    let matching = |schemas: &[Node]| {
+        schemas
+            .iter()
+            .filter(|schema| {
+                let mut scratch = Vec::new();
+                validate_node(schema, value, pointer, &mut scratch);
+                scratch.is_empty()
+            })
+            .count()
+    };
+    if !node.any_of.is_empty() && matching(&node.any_of) == 0 {
+        out.push(SchemaViolation {
+            pointer: pointer.to_string(),
+            message: "matches none of anyOf".to_string(),
+        });
+    }
+    if !node.one_of.is_empty() {
+        let count = matching(&node.one_of);
+        if count != 1 {
+            out.push(SchemaViolation {
+                pointer: pointer.to_string(),
+                message: format!("matches {} of oneOf, expected exactly 1", count),
+            });
+        }
+    }
+    if let Some(not) = &node.not {
+        if matching(std::slice::from_ref(not)) == 1 {
+            out.push(SchemaViolation {
+                pointer: pointer.to_string(),
+                message: "matches the schema in not".to_string(),
+            });
+        }
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn escape_pointer(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+// ============================================================================
+// Compatibility
+// ============================================================================
+
+fn compatibility_issues(mode: Compatibility, old: &JsonSchema, new: &JsonSchema) -> Vec<String> {
+    let mut issues = Vec::new();
+    if matches!(mode, Compatibility::Backward | Compatibility::Full) {
+        accepts_all(&old.root, &new.root, "", &mut issues);
+    }
+    if matches!(mode, Compatibility::Forward | Compatibility::Full) {
+        accepts_all(&new.root, &old.root, "", &mut issues);
+    }
+    issues.dedup();
+    issues
+}
+
+/// Conservatively check that every value valid under `writer` is valid
+/// under `reader`, recording why not in `issues`
+///
+/// Properties a writer does not declare are assumed absent, so adding an
+/// optional property to an open object is compatible either way.
+fn accepts_all(writer: &Node, reader: &Node, at: &str, issues: &mut Vec<String>) {
+    if writer.never {
+        return;
+    }
+    if reader.never {
+        note(issues, at, "no longer allows any value".to_string());
+        return;
+    }
+
+    match (&writer.types, &reader.types) {
+        (_, None) => {}
+        (None, Some(_)) => note(issues, at, "type was restricted".to_string()),
+        (Some(w), Some(r)) => {
+            for t in w {
+                if !r.iter().any(|allowed| t.within(*allowed)) {
+                    note(issues, at, format!("type {:?} is no longer allowed", t).to_lowercase());
+                }
+            }
+        }
+    }
+    match (&writer.allowed, &reader.allowed) {
+        (_, None) => {}
+        (None, Some(_)) => note(issues, at, "values were restricted to an enum".to_string()),
+        (Some(w), Some(r)) => {
+            for value in w.iter().filter(|v| !r.contains(v)) {
+                note(issues, at, format!("value {} is no longer allowed", value));
+            }
+        }
+    }
+
+    for name in reader.required.iter().filter(|name| !writer.required.contains(name)) {
+        note(issues, at, format!("property {:?} became required", name));
+    }
+    for (name, w) in &writer.properties {
+        let child = format!("{}/{}", at, escape_pointer(name));
+        match (reader.properties.get(name), &reader.additional) {
+            (Some(r), _) => accepts_all(w, r, &child, issues),
+            (None, Additional::Allowed) => {}
+            (None, Additional::Forbidden) => note(issues, at, format!("property {:?} is no longer allowed", name)),
+            (None, Additional::Schema(r)) => accepts_all(w, r, &child, issues),
+        }
+    }
+    for (name, r) in &reader.properties {
+        if writer.properties.contains_key(name) {
+            continue;
+        }
+        if let Additional::Schema(w) = &writer.additional {
+            accepts_all(w, r, &format!("{}/{}", at, escape_pointer(name)), issues);
+        }
+    }
+    match (&writer.additional, &reader.additional) {
+        (_, Additional::Allowed) | (Additional::Forbidden, _) => {}
+        (Additional::Schema(w), Additional::Schema(r)) => {
+            accepts_all(w, r, &format!("{}/*", at), issues)
+        }
+        _ => note(issues, at, "additional properties were restricted".to_string()),
+    }
+
+    match (&writer.items, &reader.items) {
+        (_, None) => {}
+        (None, Some(_)) => note(issues, at, "array items were restricted".to_string()),
+        (Some(w), Some(r)) => accepts_all(w, r, &format!("{}/*", at), issues),
+    }
+
+    let mut size = |name: &str, w: Option<usize>, r: Option<usize>, lower: bool| match (w, r) {
+        (_, None) => {}
+        (None, Some(_)) => note(issues, at, format!("{} was added", name)),
+        (Some(w), Some(r)) if (lower && w < r) || (!lower && w > r) => {
+            note(issues, at, format!("{} tightened from {} to {}", name, w, r))
+        }
+        _ => {}
+    };
+    size("minItems", writer.min_items, reader.min_items, true);
+    size("maxItems", writer.max_items, reader.max_items, false);
+    size("minLength", writer.min_length, reader.min_length, true);
+    size("maxLength", writer.max_length, reader.max_length, false);
+
+    let mut bounds = |name: &str, w: Option<Bound>, r: Option<Bound>, lower: bool| match (w, r) {
+        (_, None) => {}
+        (None, Some(_)) => note(issues, at, format!("{} was added", name)),
+        (Some(w), Some(r)) if !bound_within(w, r, lower) => {
+            note(issues, at, format!("{} tightened from {} to {}", name, w.value, r.value))
+        }
+        _ => {}
+    };
+    bounds("minimum", writer.minimum, reader.minimum, true);
+    bounds("maximum", writer.maximum, reader.maximum, false);
+
+    match (&writer.pattern, &reader.pattern) {
+        (_, None) => {}
+        (Some(w), Some(r)) if w.as_str() == r.as_str() => {}
+        _ => note(issues, at, "pattern changed".to_string()),
+    }
+    for keyword in ["allOf", "anyOf", "oneOf", "not"] {
+        let (w, r) = (writer.source.get(keyword), reader.source.get(keyword));
+        if r.is_some() && w != r {
+            note(issues, at, format!("{} changed and cannot be checked", keyword));
+        }
+    }
+}
+
+fn note(issues: &mut Vec<String>, at: &str, message: String) {
+    let at = if at.is_empty() { "/" } else { at };
+    issues.push(format!("{}: {}", at, message));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enterprise::eventsource::store::InMemoryEventStore;
+    use serde_json::json;
+
+    fn issue_schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["id", "severity"],
+            "properties": {
+                "id": {"type": "string", "minLength": 1},
+                "severity": {"enum": ["minor", "serious", "critical"]},
+                "count": {"type": "integer", "minimum": 0},
+                "tags": {"type": "array", "items": {"type": "string"}, "maxItems": 3}
+            }
+        })
+    }
+
+    #[test]
+    fn test_validate_reports_pointers() {
+        let schema = JsonSchema::compile(&issue_schema()).unwrap();
+        assert!(schema.is_valid(&json!({"id": "i1", "severity": "minor", "count": 2, "extra": true})));
+
+        let violations = schema.validate(&json!({
+            "severity": "blocker",
+            "count": -1.5,
+            "tags": ["a", 2, "c", "d"]
+        }));
+        let pointers: Vec<&str> = violations.iter().map(|v| v.pointer.as_str()).collect();
+        assert!(pointers.contains(&""), "{violations:?}");
+        assert!(pointers.contains(&"/severity"));
+        assert!(pointers.contains(&"/count"));
+        assert!(pointers.contains(&"/tags"));
+        assert!(pointers.contains(&"/tags/1"));
+
+        let closed = JsonSchema::compile(&json!({
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {"n": {"exclusiveMaximum": 10}},
+            "oneOf": [{"required": ["n"]}, {"required": ["m"]}]
+        }))
+        .unwrap();
+        assert!(closed.is_valid(&json!({"n": 9})));
+        assert!(!closed.is_valid(&json!({"n": 10})));
+        assert!(!closed.is_valid(&json!({"m": 1})));
+        assert!(!closed.is_valid(&json!({})));
+
+        assert!(JsonSchema::compile(&json!({"$ref": "#/definitions/x"})).is_err());
+        assert!(JsonSchema::compile(&json!({"type": "decimal"})).is_err());
+    }
+
+    #[test]
+    fn test_register_checks_compatibility() {
+        let registry = SchemaRegistry::new();
+        let v1 = registry.register("Issue.Detected", issue_schema()).unwrap();
+        assert_eq!(v1.version, 1);
+        assert_eq!(registry.register("Issue.Detected", issue_schema()).unwrap().version, 1);
+
+        // Adding an optional property keeps the contract
+        let mut v2 = issue_schema();
+        v2["properties"]["rule"] = json!({"type": "string"});
+        assert_eq!(registry.register("Issue.Detected", v2.clone()).unwrap().version, 2);
+
+        // Requiring it breaks payloads produced against v2
+        let mut v3 = v2.clone();
+        v3["required"] = json!(["id", "severity", "rule"]);
+        let err = registry.register("Issue.Detected", v3.clone()).unwrap_err();
+        assert!(matches!(err, SchemaError::Incompatible { version: 2, .. }), "{err}");
+
+        // Narrowing an enum breaks backward, widening breaks forward
+        let mut narrowed = v2.clone();
+        narrowed["properties"]["severity"] = json!({"enum": ["serious", "critical"]});
+        let mut widened = v2.clone();
+        widened["properties"]["severity"] = json!({"enum": ["minor", "serious", "critical", "blocker"]});
+        registry.set_compatibility("Issue.Detected", Compatibility::Forward);
+        assert!(registry.check_compatibility("Issue.Detected", &narrowed).unwrap().is_empty());
+        assert!(!registry.check_compatibility("Issue.Detected", &widened).unwrap().is_empty());
+        registry.set_compatibility("Issue.Detected", Compatibility::Backward);
+        assert!(!registry.check_compatibility("Issue.Detected", &narrowed).unwrap().is_empty());
+        assert!(registry.check_compatibility("Issue.Detected", &widened).unwrap().is_empty());
+
+        registry.set_compatibility("Issue.Detected", Compatibility::None);
+        assert_eq!(registry.register("Issue.Detected", v3).unwrap().version, 3);
+        assert_eq!(registry.latest("Issue.Detected").unwrap().version, 3);
+        assert_eq!(registry.version("Issue.Detected", 1).unwrap().schema, issue_schema());
+        assert!(registry.version("Issue.Detected", 0).is_none());
+        assert_eq!(registry.subjects(), vec!["Issue.Detected".to_string()]);
+    }
+
+    #[test]
+    fn test_validation_counts_rejections() {
+        let metrics = MetricRegistry::new();
+        let registry = SchemaRegistry::new().with_metrics(&metrics);
+        registry.register("Issue.Detected", issue_schema()).unwrap();
+
+        registry
+            .validate("Issue.Detected", &json!({"id": "i1", "severity": "minor"}))
+            .unwrap();
+        let err = registry
+            .validate("Issue.Detected", &json!({"id": "", "severity": "minor"}))
+            .unwrap_err();
+        assert!(matches!(err, SchemaError::Rejected { version: 1, .. }));
+        assert!(registry.validate("Unregistered", &json!(42)).is_ok());
+
+        let stats = registry.stats("Issue.Detected").unwrap();
+        assert_eq!((stats.validated, stats.rejected), (2, 1));
+        assert!(stats.last_rejection.unwrap().starts_with("/id"));
+        let exported = metrics.prometheus_export();
+        assert!(exported.contains("schema_validations_total 2\n"), "{exported}");
+        assert!(exported.contains("schema_rejections_total 1\n"), "{exported}");
+    }
+
+    #[tokio::test]
+    async fn test_validating_event_store_rejects_batch() {
+        let registry = Arc::new(SchemaRegistry::new());
+        registry.register("Issue.Detected", issue_schema()).unwrap();
+        let store = ValidatingEventStore::new(InMemoryEventStore::new(), registry.clone());
+
+        let event = |payload: Value| EventData {
+            stream_id: "site-1".to_string(),
+            event_type: "Issue.Detected".to_string(),
+            data: serde_json::to_vec(&payload).unwrap(),
+            expected_version: -1,
+            correlation_id: None,
+            causation_id: None,
+            metadata: HashMap::new(),
+        };
+
+        let result = store
+            .append_events(vec![
+                event(json!({"id": "i1", "severity": "minor"})),
+                event(json!({"id": "i2"})),
+            ])
+            .await;
+        assert!(result.is_err());
+        assert_eq!(store.get_global_sequence().await.unwrap(), 0);
+
+        let stored = store
+            .append_events(vec![event(json!({"id": "i1", "severity": "minor"}))])
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(registry.stats("Issue.Detected").unwrap().rejected, 1);
+    }
+}
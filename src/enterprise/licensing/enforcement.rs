@@ -475,8 +475,15 @@ impl EnforcementEngine {
             }
         }
 
-        // Track usage
+        drop(entitlement_manager);
+
+        // Track usage, attributing it to the user's seat
         if self.policy.track_usage {
+            if let Some(user_id) = &user_id {
+                self.entitlement_manager
+                    .write()
+                    .record_feature_use(user_id, feature);
+            }
             self.usage_tracker.track_usage(feature, user_id);
         }
 
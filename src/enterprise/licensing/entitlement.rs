@@ -10,6 +10,7 @@ use thiserror::Error;
 use uuid::Uuid;
 
 use super::license::{License, LicenseFeature, LicenseError};
+use super::seats::SeatAnalytics;
 
 /// Errors that can occur with entitlements
 #[derive(Debug, Error)]
@@ -387,6 +388,9 @@ pub struct EntitlementManager {
 
     /// Feature overrides (can grant or deny specific features)
     pub feature_overrides: HashMap<LicenseFeature, bool>,
+
+    /// Per-seat activity and concurrency history
+    pub analytics: SeatAnalytics,
 }
 
 impl EntitlementManager {
@@ -426,6 +430,7 @@ impl EntitlementManager {
             grace_period: GracePeriod::default(),
             quotas,
             feature_overrides: HashMap::new(),
+            analytics: SeatAnalytics::new(),
         }
     }
 
@@ -466,12 +471,40 @@ impl EntitlementManager {
 
     /// Acquire a seat for a user
     pub fn acquire_seat(&mut self, user_id: String) -> Result<(), EntitlementError> {
-        self.seats.acquire_seat(user_id)
+        self.seats.acquire_seat(user_id.clone())?;
+        self.analytics.record_acquire(&user_id, Utc::now());
+        Ok(())
     }
 
     /// Release a seat
     pub fn release_seat(&mut self, user_id: &str) {
         self.seats.release_seat(user_id);
+        self.analytics.record_release(user_id, Utc::now());
+    }
+
+    /// Record activity by a seat holder
+    pub fn record_activity(&mut self, user_id: &str) {
+        if let Some(session) = self.seats.active_users.get_mut(user_id) {
+            session.update_activity();
+        }
+        self.analytics.record_activity(user_id, Utc::now());
+    }
+
+    /// Record use of a licensed feature by a seat holder
+    pub fn record_feature_use(&mut self, user_id: &str, feature: LicenseFeature) {
+        if let Some(session) = self.seats.active_users.get_mut(user_id) {
+            session.update_activity();
+        }
+        self.analytics.record_feature_use(user_id, feature, Utc::now());
+    }
+
+    /// Take a seat back: release any session and drop the user from the
+    /// named user list
+    pub fn reclaim_seat(&mut self, user_id: &str) {
+        self.release_seat(user_id);
+        if let Some(named_users) = &mut self.seats.named_users {
+            named_users.retain(|named| named != user_id);
+        }
     }
 
     /// Consume usage quota
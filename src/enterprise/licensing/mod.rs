@@ -5,7 +5,10 @@
 //!
 //! - License key generation and validation with cryptographic signing
 //! - Online and offline activation with hardware fingerprinting
+//! - Air-gapped licensing with signed license files, activation request and
+//!   response files, reconcilable usage logs and clock-tamper detection
 //! - Entitlement management with seat limits and usage quotas
+//! - Seat usage analytics with idle-seat reclamation and monthly seat reports
 //! - Subscription management with renewal and billing integration
 //! - Comprehensive license validation
 //! - License enforcement with feature gating and violation handling
@@ -23,7 +26,9 @@
 //! - **license**: Core license types and structures
 //! - **key**: License key generation, encoding, and validation
 //! - **activation**: Online/offline activation with hardware binding
+//! - **offline**: License files and usage reconciliation for air-gapped sites
 //! - **entitlement**: Feature entitlements, seat limits, and usage quotas
+//! - **seats**: Seat activity reporting and idle-seat reclamation
 //! - **subscription**: Subscription lifecycle and billing management
 //! - **validation**: Comprehensive license validation
 //! - **enforcement**: Feature gating and license enforcement
@@ -116,9 +121,15 @@ pub mod key;
 // Activation system
 pub mod activation;
 
+// Air-gapped licensing
+pub mod offline;
+
 // Entitlement management
 pub mod entitlement;
 
+// Seat analytics and reclamation
+pub mod seats;
+
 // Subscription management
 pub mod subscription;
 
@@ -140,11 +151,23 @@ pub use activation::{
     ActivationResponse, HardwareFingerprint,
 };
 
+pub use offline::{
+    ClockGuard, LicenseFile, LicenseGrant, OfflineActivationRequest, OfflineActivationResponse,
+    OfflineError, OfflineLicenseIssuer, OfflineLicenseManager, OfflineState, UsageLedger, UsageLog,
+    UsageLogEntry, UsageSummary,
+};
+
 pub use entitlement::{
     EntitlementError, EntitlementManager, GracePeriod, GracePeriodStatus, SeatEntitlement,
     UsageQuota, UserSession,
 };
 
+pub use seats::{
+    CaseStatus, ConcurrencyPeak, DailyPeak, FeatureUse, ReclamationCase, ReclamationPolicy,
+    ReclamationSuggestion, ReportPeriod, SeatActivity, SeatAnalytics, SeatError, SeatManager,
+    SeatReport, SeatState, SeatUsage, SEAT_RECLAMATION_ACTOR,
+};
+
 pub use subscription::{
     BillingInterval, Invoice, InvoiceStatus, Subscription, SubscriptionError, SubscriptionManager,
     SubscriptionPlan, SubscriptionStatus,
@@ -0,0 +1,960 @@
+//! # Offline Licensing
+//!
+//! This module licenses air-gapped sites that can never reach the activation
+//! server. Everything travels as text files carried across the gap:
+//!
+//! - **License files**: a license and the hardware IDs it may run on, signed
+//!   with the vendor's Ed25519 key
+//! - **Activation request/response files**: the site exports a request with
+//!   its hardware fingerprint, the vendor answers with a license file bound
+//!   to that machine
+//! - **Usage logs**: a hash-chained record of feature use, exported for the
+//!   vendor to reconcile later; importing overlapping or repeated exports
+//!   from any number of machines never double counts
+//! - **Clock-tamper detection**: the latest time seen is remembered, so
+//!   winding the clock back to stretch an expiring license is caught
+//!
+//! Files are armored base64 between `-----BEGIN ...-----` lines so they
+//! survive email, removable media and copy-paste. Signed payloads keep the
+//! exact bytes that were signed, so verification never depends on how a
+//! structure happens to re-serialize.
+//!
+//! The usage chain detects edited, reordered and deleted entries; it cannot
+//! stop a determined user from rewriting the whole log, which is caught when
+//! the rewritten export forks from one the vendor already holds.
+
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+use uuid::Uuid;
+
+use super::activation::{ActivationError, ActivationMethod, ActivationRecord, HardwareFingerprint};
+use super::license::{License, LicenseError, LicenseFeature};
+
+const LICENSE_LABEL: &str = "CADDY LICENSE";
+const REQUEST_LABEL: &str = "CADDY ACTIVATION REQUEST";
+const RESPONSE_LABEL: &str = "CADDY ACTIVATION RESPONSE";
+const USAGE_LABEL: &str = "CADDY USAGE REPORT";
+
+/// Base64 characters per armored line
+const ARMOR_WIDTH: usize = 64;
+
+/// Default allowance for clock drift and corrections, in seconds
+pub const DEFAULT_CLOCK_TOLERANCE_SECS: i64 = 300;
+
+/// Errors that can occur during offline licensing
+#[derive(Debug, Error)]
+pub enum OfflineError {
+    #[error("Malformed {0} file: {1}")]
+    Malformed(&'static str, String),
+
+    #[error("Signature verification failed")]
+    InvalidSignature,
+
+    #[error("License file is not issued for this hardware")]
+    HardwareMismatch,
+
+    #[error("Response does not answer this activation request")]
+    RequestMismatch,
+
+    #[error("No license file installed")]
+    NotInstalled,
+
+    #[error("License file expired on {0}")]
+    Expired(DateTime<Utc>),
+
+    #[error("License has been revoked")]
+    Revoked,
+
+    #[error("System clock tampering detected: {0}")]
+    ClockTampered(String),
+
+    #[error("Usage log broken at entry {0}")]
+    BrokenChain(u64),
+
+    #[error("Usage log diverges from the one already reconciled at entry {0}")]
+    ForkedLog(u64),
+
+    #[error("Activation error: {0}")]
+    ActivationError(#[from] ActivationError),
+
+    #[error("License error: {0}")]
+    LicenseError(#[from] LicenseError),
+}
+
+/// Wrap a value as armored base64 JSON
+fn armor<T: Serialize>(label: &'static str, value: &T) -> Result<String, OfflineError> {
+    let json =
+        serde_json::to_vec(value).map_err(|e| OfflineError::Malformed(label, e.to_string()))?;
+    let encoded = general_purpose::STANDARD.encode(json);
+    let mut text = format!("-----BEGIN {}-----\n", label);
+    for line in encoded.as_bytes().chunks(ARMOR_WIDTH) {
+        text.push_str(&String::from_utf8_lossy(line));
+        text.push('\n');
+    }
+    text.push_str(&format!("-----END {}-----\n", label));
+    Ok(text)
+}
+
+/// Read a value back from armored text, ignoring anything around the block
+fn dearmor<T: DeserializeOwned>(label: &'static str, text: &str) -> Result<T, OfflineError> {
+    let (begin, end) = (
+        format!("-----BEGIN {}-----", label),
+        format!("-----END {}-----", label),
+    );
+    let malformed = |detail: &str| OfflineError::Malformed(label, detail.to_string());
+    let start = text
+        .find(&begin)
+        .ok_or_else(|| malformed("missing BEGIN line"))?
+        + begin.len();
+    let stop = text[start..]
+        .find(&end)
+        .ok_or_else(|| malformed("missing END line"))?
+        + start;
+    let body: String = text[start..stop]
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let json = general_purpose::STANDARD
+        .decode(body)
+        .map_err(|e| malformed(&e.to_string()))?;
+    serde_json::from_slice(&json).map_err(|e| malformed(&e.to_string()))
+}
+
+/// What a license file grants
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseGrant {
+    /// The license
+    pub license: License,
+
+    /// Hardware IDs the file may be installed on
+    pub hardware_ids: Vec<String>,
+
+    /// Issue timestamp
+    pub issued_at: DateTime<Utc>,
+
+    /// Activation request this grant answers, if any
+    pub request_id: Option<Uuid>,
+}
+
+impl LicenseGrant {
+    /// Whether the grant covers a machine
+    pub fn covers(&self, hardware_id: &str) -> bool {
+        self.hardware_ids.iter().any(|id| id == hardware_id)
+    }
+}
+
+/// Signed license file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LicenseFile {
+    /// Base64 of the signed grant JSON
+    pub payload: String,
+
+    /// Base64 Ed25519 signature over the decoded payload bytes
+    pub signature: String,
+}
+
+impl LicenseFile {
+    /// Sign a grant
+    pub fn issue(grant: &LicenseGrant, signing_key: &SigningKey) -> Result<Self, OfflineError> {
+        let bytes = serde_json::to_vec(grant)
+            .map_err(|e| OfflineError::Malformed(LICENSE_LABEL, e.to_string()))?;
+        let signature = signing_key.sign(&bytes);
+
+        Ok(Self {
+            payload: general_purpose::STANDARD.encode(&bytes),
+            signature: general_purpose::STANDARD.encode(signature.to_bytes()),
+        })
+    }
+
+    /// Check the signature and read the grant
+    pub fn verify(&self, verifying_key: &VerifyingKey) -> Result<LicenseGrant, OfflineError> {
+        let bytes = general_purpose::STANDARD
+            .decode(&self.payload)
+            .map_err(|_| OfflineError::InvalidSignature)?;
+        let signature: [u8; 64] = general_purpose::STANDARD
+            .decode(&self.signature)
+            .ok()
+            .and_then(|s| s.try_into().ok())
+            .ok_or(OfflineError::InvalidSignature)?;
+        verifying_key
+            .verify(&bytes, &Signature::from_bytes(&signature))
+            .map_err(|_| OfflineError::InvalidSignature)?;
+
+        serde_json::from_slice(&bytes)
+            .map_err(|e| OfflineError::Malformed(LICENSE_LABEL, e.to_string()))
+    }
+
+    /// Armored text for the file
+    pub fn to_armored(&self) -> Result<String, OfflineError> {
+        armor(LICENSE_LABEL, self)
+    }
+
+    /// Parse armored text
+    pub fn from_armored(text: &str) -> Result<Self, OfflineError> {
+        dearmor(LICENSE_LABEL, text)
+    }
+}
+
+/// Activation request exported from an air-gapped machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfflineActivationRequest {
+    /// Request ID
+    pub id: Uuid,
+
+    /// License ID
+    pub license_id: Uuid,
+
+    /// License key
+    pub license_key: String,
+
+    /// Hardware fingerprint of the requesting machine
+    pub hardware_id: String,
+
+    /// Device name (optional)
+    pub device_name: Option<String>,
+
+    /// Product version
+    pub product_version: String,
+
+    /// Created timestamp
+    pub created_at: DateTime<Utc>,
+}
+
+impl OfflineActivationRequest {
+    /// Armored text for the file
+    pub fn to_armored(&self) -> Result<String, OfflineError> {
+        armor(REQUEST_LABEL, self)
+    }
+
+    /// Parse armored text
+    pub fn from_armored(text: &str) -> Result<Self, OfflineError> {
+        dearmor(REQUEST_LABEL, text)
+    }
+}
+
+/// Vendor's answer to an activation request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfflineActivationResponse {
+    /// Request being answered
+    pub request_id: Uuid,
+
+    /// License file bound to the requesting machine
+    pub license_file: LicenseFile,
+}
+
+impl OfflineActivationResponse {
+    /// Armored text for the file
+    pub fn to_armored(&self) -> Result<String, OfflineError> {
+        armor(RESPONSE_LABEL, self)
+    }
+
+    /// Parse armored text
+    pub fn from_armored(text: &str) -> Result<Self, OfflineError> {
+        dearmor(RESPONSE_LABEL, text)
+    }
+}
+
+/// Vendor side: issues license files and answers activation requests
+pub struct OfflineLicenseIssuer {
+    signing_key: SigningKey,
+}
+
+impl OfflineLicenseIssuer {
+    /// Create an issuer with the vendor's signing key
+    pub fn new(signing_key: SigningKey) -> Self {
+        Self { signing_key }
+    }
+
+    /// Create an issuer from secret key bytes
+    pub fn from_bytes(secret_bytes: &[u8; 32]) -> Self {
+        Self::new(SigningKey::from_bytes(secret_bytes))
+    }
+
+    /// Public key sites verify against
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    /// Issue a license file for the given machines without a request, e.g.
+    /// for sites that supply their hardware IDs up front
+    pub fn issue(
+        &self,
+        license: &License,
+        hardware_ids: Vec<String>,
+    ) -> Result<LicenseFile, OfflineError> {
+        let grant = LicenseGrant {
+            license: license.clone(),
+            hardware_ids,
+            issued_at: Utc::now(),
+            request_id: None,
+        };
+        LicenseFile::issue(&grant, &self.signing_key)
+    }
+
+    /// Answer an activation request, consuming an activation of `license`
+    /// unless the requesting machine already holds one
+    pub fn respond(
+        &self,
+        request: &OfflineActivationRequest,
+        license: &mut License,
+    ) -> Result<OfflineActivationResponse, OfflineError> {
+        if request.license_id != license.id || request.license_key != license.key {
+            return Err(OfflineError::RequestMismatch);
+        }
+        if license.revoked {
+            return Err(OfflineError::Revoked);
+        }
+        if license.hardware_id.as_deref() != Some(request.hardware_id.as_str()) {
+            license.activate(request.hardware_id.clone())?;
+        }
+
+        let grant = LicenseGrant {
+            license: license.clone(),
+            hardware_ids: vec![request.hardware_id.clone()],
+            issued_at: Utc::now(),
+            request_id: Some(request.id),
+        };
+
+        Ok(OfflineActivationResponse {
+            request_id: request.id,
+            license_file: LicenseFile::issue(&grant, &self.signing_key)?,
+        })
+    }
+}
+
+/// Remembers the latest time seen to catch clocks being wound back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockGuard {
+    /// Latest time observed
+    pub high_water: Option<DateTime<Utc>>,
+
+    /// Allowance for drift and clock corrections, in seconds
+    pub tolerance_secs: i64,
+}
+
+impl Default for ClockGuard {
+    fn default() -> Self {
+        Self {
+            high_water: None,
+            tolerance_secs: DEFAULT_CLOCK_TOLERANCE_SECS,
+        }
+    }
+}
+
+impl ClockGuard {
+    /// Record `now`, failing if it is earlier than a time already seen
+    pub fn observe(&mut self, now: DateTime<Utc>) -> Result<(), OfflineError> {
+        if let Some(high_water) = self.high_water {
+            if now + Duration::seconds(self.tolerance_secs) < high_water {
+                return Err(OfflineError::ClockTampered(format!(
+                    "clock reads {}, but {} was already seen",
+                    now.to_rfc3339(),
+                    high_water.to_rfc3339()
+                )));
+            }
+        }
+        self.high_water = Some(self.high_water.map_or(now, |seen| seen.max(now)));
+        Ok(())
+    }
+
+    /// Fail if `now` is before `at`, which the clock must already have passed
+    pub fn require_after(
+        &self,
+        now: DateTime<Utc>,
+        at: DateTime<Utc>,
+        what: &str,
+    ) -> Result<(), OfflineError> {
+        if now + Duration::seconds(self.tolerance_secs) < at {
+            return Err(OfflineError::ClockTampered(format!(
+                "clock reads {}, before {} at {}",
+                now.to_rfc3339(),
+                what,
+                at.to_rfc3339()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A recorded use of the product
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageLogEntry {
+    /// Position in the log, from zero
+    pub sequence: u64,
+
+    /// When it happened
+    pub at: DateTime<Utc>,
+
+    /// What happened, e.g. "session_start" or "export"
+    pub action: String,
+
+    /// Feature used (optional)
+    pub feature: Option<LicenseFeature>,
+
+    /// User (optional)
+    pub user: Option<String>,
+
+    /// Hash of the previous entry
+    pub previous: String,
+
+    /// Hash of this entry
+    pub hash: String,
+}
+
+impl UsageLogEntry {
+    fn compute_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.previous.as_bytes());
+        hasher.update(self.sequence.to_be_bytes());
+        hasher.update(self.at.to_rfc3339().as_bytes());
+        hasher.update(self.action.as_bytes());
+        hasher.update([0]);
+        if let Some(feature) = self.feature {
+            hasher.update(feature.name().as_bytes());
+        }
+        hasher.update([0]);
+        if let Some(user) = &self.user {
+            hasher.update(user.as_bytes());
+        }
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// Hash-chained usage log of one machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageLog {
+    /// License ID
+    pub license_id: Uuid,
+
+    /// Machine the log was kept on
+    pub hardware_id: String,
+
+    /// Entries, oldest first
+    pub entries: Vec<UsageLogEntry>,
+}
+
+impl UsageLog {
+    /// Start an empty log
+    pub fn new(license_id: Uuid, hardware_id: String) -> Self {
+        Self {
+            license_id,
+            hardware_id,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Hash the first entry chains from
+    fn genesis(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.license_id.as_bytes());
+        hasher.update(self.hardware_id.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    /// Append an entry
+    pub fn append(
+        &mut self,
+        at: DateTime<Utc>,
+        action: impl Into<String>,
+        feature: Option<LicenseFeature>,
+        user: Option<String>,
+    ) -> &UsageLogEntry {
+        let previous = self
+            .entries
+            .last()
+            .map_or_else(|| self.genesis(), |e| e.hash.clone());
+        let mut entry = UsageLogEntry {
+            sequence: self.entries.len() as u64,
+            at,
+            action: action.into(),
+            feature,
+            user,
+            previous,
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+        self.entries.push(entry);
+        &self.entries[self.entries.len() - 1]
+    }
+
+    /// Check the chain, sequence numbers and time order
+    pub fn verify(&self) -> Result<(), OfflineError> {
+        let mut previous = self.genesis();
+        let mut last_at: Option<DateTime<Utc>> = None;
+        for (i, entry) in self.entries.iter().enumerate() {
+            let broken = entry.sequence != i as u64
+                || entry.previous != previous
+                || entry.hash != entry.compute_hash()
+                || last_at.is_some_and(|at| entry.at < at);
+            if broken {
+                return Err(OfflineError::BrokenChain(i as u64));
+            }
+            previous = entry.hash.clone();
+            last_at = Some(entry.at);
+        }
+        Ok(())
+    }
+
+    /// Armored text for export
+    pub fn to_armored(&self) -> Result<String, OfflineError> {
+        armor(USAGE_LABEL, self)
+    }
+
+    /// Parse an armored export
+    pub fn from_armored(text: &str) -> Result<Self, OfflineError> {
+        dearmor(USAGE_LABEL, text)
+    }
+}
+
+/// Persistent state of an offline installation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OfflineState {
+    /// Installed license file
+    pub license_file: Option<LicenseFile>,
+
+    /// Clock high-water mark
+    pub clock: ClockGuard,
+
+    /// Usage recorded since installation
+    pub usage: Option<UsageLog>,
+}
+
+/// Site side: installs license files and checks them without a network
+pub struct OfflineLicenseManager {
+    verifying_key: VerifyingKey,
+    hardware_id: String,
+    state: OfflineState,
+}
+
+impl OfflineLicenseManager {
+    /// Create a manager for a machine with the given hardware ID
+    pub fn new(verifying_key: VerifyingKey, hardware_id: String) -> Self {
+        Self {
+            verifying_key,
+            hardware_id,
+            state: OfflineState::default(),
+        }
+    }
+
+    /// Create a manager for this machine's hardware fingerprint
+    pub fn for_this_machine(verifying_key: VerifyingKey) -> Result<Self, OfflineError> {
+        let hardware_id = HardwareFingerprint::new()?.generate_id();
+        Ok(Self::new(verifying_key, hardware_id))
+    }
+
+    /// Resume with saved state
+    pub fn with_state(mut self, state: OfflineState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// State to persist between runs
+    pub fn state(&self) -> &OfflineState {
+        &self.state
+    }
+
+    /// Hardware ID of this machine
+    pub fn hardware_id(&self) -> &str {
+        &self.hardware_id
+    }
+
+    /// Build an activation request to carry to the vendor
+    pub fn create_request(
+        &self,
+        license: &License,
+        device_name: Option<String>,
+    ) -> OfflineActivationRequest {
+        OfflineActivationRequest {
+            id: Uuid::new_v4(),
+            license_id: license.id,
+            license_key: license.key.clone(),
+            hardware_id: self.hardware_id.clone(),
+            device_name,
+            product_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Install the vendor's response to `request`
+    pub fn install_response(
+        &mut self,
+        request: &OfflineActivationRequest,
+        response: &OfflineActivationResponse,
+    ) -> Result<ActivationRecord, OfflineError> {
+        let grant = response.license_file.verify(&self.verifying_key)?;
+        if response.request_id != request.id || grant.request_id != Some(request.id) {
+            return Err(OfflineError::RequestMismatch);
+        }
+        let license = self.install_license_file(response.license_file.clone())?;
+
+        let mut record = ActivationRecord::new(
+            license.id,
+            self.hardware_id.clone(),
+            ActivationMethod::Offline,
+        );
+        record.device_name = request.device_name.clone();
+        record
+            .metadata
+            .insert("request_id".to_string(), request.id.to_string());
+        Ok(record)
+    }
+
+    /// Install a license file issued for this machine
+    pub fn install_license_file(&mut self, file: LicenseFile) -> Result<License, OfflineError> {
+        let now = Utc::now();
+        let grant = self.check_file(&file, now)?;
+
+        let license_id = grant.license.id;
+        if self.state.usage.as_ref().map(|log| log.license_id) != Some(license_id) {
+            self.state.usage = Some(UsageLog::new(license_id, self.hardware_id.clone()));
+        }
+        self.state.license_file = Some(file);
+        self.record_usage_at(now, "license_installed", None, None)?;
+        Ok(grant.license)
+    }
+
+    /// Check the installed license against the current time
+    pub fn check(&mut self) -> Result<License, OfflineError> {
+        self.check_at(Utc::now())
+    }
+
+    /// Check the installed license as of `now`
+    pub fn check_at(&mut self, now: DateTime<Utc>) -> Result<License, OfflineError> {
+        let file = self
+            .state
+            .license_file
+            .clone()
+            .ok_or(OfflineError::NotInstalled)?;
+        Ok(self.check_file(&file, now)?.license)
+    }
+
+    fn check_file(
+        &mut self,
+        file: &LicenseFile,
+        now: DateTime<Utc>,
+    ) -> Result<LicenseGrant, OfflineError> {
+        let grant = file.verify(&self.verifying_key)?;
+        if !grant.covers(&self.hardware_id) {
+            return Err(OfflineError::HardwareMismatch);
+        }
+
+        self.state.clock.observe(now)?;
+        self.state
+            .clock
+            .require_after(now, grant.issued_at, "the license file was issued")?;
+
+        let license = &grant.license;
+        if license.revoked {
+            return Err(OfflineError::Revoked);
+        }
+        if let Some(expiry) = license.expiry {
+            if now > expiry {
+                return Err(OfflineError::Expired(expiry));
+            }
+        }
+        Ok(grant)
+    }
+
+    /// Record a use of the product now
+    pub fn record_usage(
+        &mut self,
+        action: &str,
+        feature: Option<LicenseFeature>,
+        user: Option<String>,
+    ) -> Result<(), OfflineError> {
+        self.record_usage_at(Utc::now(), action, feature, user)
+    }
+
+    /// Record a use of the product at `at`
+    pub fn record_usage_at(
+        &mut self,
+        at: DateTime<Utc>,
+        action: &str,
+        feature: Option<LicenseFeature>,
+        user: Option<String>,
+    ) -> Result<(), OfflineError> {
+        self.state.clock.observe(at)?;
+        let log = self
+            .state
+            .usage
+            .as_mut()
+            .ok_or(OfflineError::NotInstalled)?;
+        // Entries stay in time order even within the clock tolerance
+        let at = log.entries.last().map_or(at, |last| at.max(last.at));
+        log.append(at, action, feature, user);
+        Ok(())
+    }
+
+    /// Usage log to export for reconciliation
+    pub fn usage_report(&self) -> Result<&UsageLog, OfflineError> {
+        self.state.usage.as_ref().ok_or(OfflineError::NotInstalled)
+    }
+}
+
+/// Totals for one license across all reconciled machines
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageSummary {
+    /// Entries reconciled
+    pub entries: u64,
+
+    /// Machines that reported
+    pub machines: usize,
+
+    /// Earliest entry
+    pub first_at: Option<DateTime<Utc>>,
+
+    /// Latest entry
+    pub last_at: Option<DateTime<Utc>>,
+
+    /// Entries per action
+    pub by_action: BTreeMap<String, u64>,
+
+    /// Entries per feature
+    pub by_feature: BTreeMap<String, u64>,
+}
+
+/// Vendor side: merges exported usage logs
+///
+/// Each machine's log only grows, so an import keeps what is already held
+/// and appends the entries past it. Re-importing an export, or an older one,
+/// adds nothing; an export that disagrees with held entries is rejected.
+#[derive(Debug, Default)]
+pub struct UsageLedger {
+    logs: HashMap<(Uuid, String), Vec<UsageLogEntry>>,
+}
+
+impl UsageLedger {
+    /// Create an empty ledger
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merge an export, returning the number of new entries
+    pub fn import(&mut self, log: &UsageLog) -> Result<usize, OfflineError> {
+        log.verify()?;
+        let held = self
+            .logs
+            .entry((log.license_id, log.hardware_id.clone()))
+            .or_default();
+        if let Some(fork) = held
+            .iter()
+            .zip(&log.entries)
+            .find(|(a, b)| a.hash != b.hash)
+        {
+            return Err(OfflineError::ForkedLog(fork.0.sequence));
+        }
+
+        let new = log.entries.len().saturating_sub(held.len());
+        held.extend(log.entries.iter().skip(held.len()).cloned());
+        Ok(new)
+    }
+
+    /// Totals for a license
+    pub fn summary(&self, license_id: Uuid) -> UsageSummary {
+        let mut summary = UsageSummary::default();
+        for ((id, _), entries) in &self.logs {
+            if *id != license_id {
+                continue;
+            }
+            summary.machines += 1;
+            for entry in entries {
+                summary.entries += 1;
+                summary.first_at = Some(summary.first_at.map_or(entry.at, |at| at.min(entry.at)));
+                summary.last_at = Some(summary.last_at.map_or(entry.at, |at| at.max(entry.at)));
+                *summary.by_action.entry(entry.action.clone()).or_default() += 1;
+                if let Some(feature) = entry.feature {
+                    *summary
+                        .by_feature
+                        .entry(feature.name().to_string())
+                        .or_default() += 1;
+                }
+            }
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enterprise::licensing::key::KeyGenerator;
+    use crate::enterprise::licensing::license::{LicenseType, LicenseeInfo};
+
+    fn license() -> License {
+        let licensee = LicenseeInfo {
+            name: "Test User".to_string(),
+            email: "test@example.com".to_string(),
+            organization: Some("Test Corp".to_string()),
+            country: None,
+        };
+        License::new(
+            "TEST-KEY-123".to_string(),
+            LicenseType::Professional,
+            licensee,
+        )
+    }
+
+    fn issuer() -> OfflineLicenseIssuer {
+        OfflineLicenseIssuer::from_bytes(&KeyGenerator::new().secret_key_bytes())
+    }
+
+    #[test]
+    fn test_offline_activation_flow() {
+        let issuer = issuer();
+        let mut vendor_license = license();
+        let mut site = OfflineLicenseManager::new(issuer.verifying_key(), "hardware-1".to_string());
+
+        // Files cross the gap as armored text
+        let request_text = site
+            .create_request(&vendor_license, Some("Drafting PC".to_string()))
+            .to_armored()
+            .unwrap();
+        assert!(request_text.starts_with("-----BEGIN CADDY ACTIVATION REQUEST-----"));
+        let request = OfflineActivationRequest::from_armored(&request_text).unwrap();
+
+        let response_text = issuer
+            .respond(&request, &mut vendor_license)
+            .unwrap()
+            .to_armored()
+            .unwrap();
+        assert_eq!(vendor_license.activation_count, 1);
+        let response = OfflineActivationResponse::from_armored(&response_text).unwrap();
+
+        let record = site.install_response(&request, &response).unwrap();
+        assert_eq!(record.method, ActivationMethod::Offline);
+        assert_eq!(record.license_id, vendor_license.id);
+        assert!(site.check().unwrap().activated);
+
+        // A file for one machine does not install on another
+        let mut other =
+            OfflineLicenseManager::new(issuer.verifying_key(), "hardware-2".to_string());
+        assert!(matches!(
+            other.install_license_file(response.license_file.clone()),
+            Err(OfflineError::HardwareMismatch)
+        ));
+
+        // Nor does a response to some other request
+        let stray = site.create_request(&vendor_license, None);
+        assert!(matches!(
+            site.install_response(&stray, &response),
+            Err(OfflineError::RequestMismatch)
+        ));
+
+        // Edited payloads fail the signature
+        let mut forged = response.license_file.clone();
+        let mut grant = forged.verify(&issuer.verifying_key()).unwrap();
+        grant.hardware_ids.push("hardware-2".to_string());
+        forged.payload = general_purpose::STANDARD.encode(serde_json::to_vec(&grant).unwrap());
+        assert!(matches!(
+            other.install_license_file(forged),
+            Err(OfflineError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_clock_tamper_detection() {
+        let issuer = issuer();
+        let mut expiring = license();
+        expiring.expiry = Some(Utc::now() + Duration::days(30));
+        let file = issuer
+            .issue(&expiring, vec!["hardware-1".to_string()])
+            .unwrap();
+
+        let mut site = OfflineLicenseManager::new(issuer.verifying_key(), "hardware-1".to_string());
+        site.install_license_file(file).unwrap();
+
+        let later = Utc::now() + Duration::days(40);
+        assert!(matches!(
+            site.check_at(later),
+            Err(OfflineError::Expired(_))
+        ));
+
+        // Winding the clock back to before the expiry is caught
+        let wound_back = Utc::now() + Duration::days(1);
+        assert!(matches!(
+            site.check_at(wound_back),
+            Err(OfflineError::ClockTampered(_))
+        ));
+
+        // The high-water mark survives a restart
+        let state = site.state().clone();
+        let mut restarted =
+            OfflineLicenseManager::new(issuer.verifying_key(), "hardware-1".to_string())
+                .with_state(state);
+        assert!(matches!(
+            restarted.check_at(wound_back),
+            Err(OfflineError::ClockTampered(_))
+        ));
+
+        // So is a clock set before the file was issued
+        let mut fresh =
+            OfflineLicenseManager::new(issuer.verifying_key(), "hardware-1".to_string())
+                .with_state(OfflineState {
+                    license_file: restarted.state().license_file.clone(),
+                    ..OfflineState::default()
+                });
+        assert!(matches!(
+            fresh.check_at(Utc::now() - Duration::days(2)),
+            Err(OfflineError::ClockTampered(_))
+        ));
+    }
+
+    #[test]
+    fn test_usage_reconciliation() {
+        let issuer = issuer();
+        let license = license();
+        let file = issuer
+            .issue(&license, vec!["hardware-1".to_string()])
+            .unwrap();
+        let mut site = OfflineLicenseManager::new(issuer.verifying_key(), "hardware-1".to_string());
+        site.install_license_file(file).unwrap();
+
+        site.record_usage("session_start", None, Some("alice".to_string()))
+            .unwrap();
+        site.record_usage(
+            "export",
+            Some(LicenseFeature::PDFGeneration),
+            Some("alice".to_string()),
+        )
+        .unwrap();
+
+        let mut ledger = UsageLedger::new();
+        let export =
+            UsageLog::from_armored(&site.usage_report().unwrap().to_armored().unwrap()).unwrap();
+        assert_eq!(ledger.import(&export).unwrap(), 3);
+        // Importing the same export again changes nothing
+        assert_eq!(ledger.import(&export).unwrap(), 0);
+
+        site.record_usage("export", Some(LicenseFeature::PDFGeneration), None)
+            .unwrap();
+        assert_eq!(ledger.import(site.usage_report().unwrap()).unwrap(), 1);
+        assert_eq!(ledger.import(&export).unwrap(), 0);
+
+        let summary = ledger.summary(license.id);
+        assert_eq!(summary.entries, 4);
+        assert_eq!(summary.machines, 1);
+        assert_eq!(summary.by_action["export"], 2);
+        assert_eq!(summary.by_feature["PDF Generation"], 2);
+
+        // Deleting an entry breaks the chain
+        let mut edited = site.usage_report().unwrap().clone();
+        edited.entries.remove(1);
+        assert!(matches!(
+            ledger.import(&edited),
+            Err(OfflineError::BrokenChain(1))
+        ));
+
+        // A rewritten log forks from what is already reconciled
+        let mut rewritten = UsageLog::new(license.id, "hardware-1".to_string());
+        rewritten.append(Utc::now(), "license_installed", None, None);
+        assert!(matches!(
+            ledger.import(&rewritten),
+            Err(OfflineError::ForkedLog(0))
+        ));
+    }
+}
@@ -0,0 +1,967 @@
+//! # Seat Analytics and Reclamation
+//!
+//! Tracks how licensed seats are actually used (when each holder was last
+//! active, which features they used and how many seats were held at once)
+//! and turns that into periodic seat reports and a reclamation workflow for
+//! idle seats.
+//!
+//! Idle seats are never taken back silently. A review opens a
+//! [`ReclamationCase`] for each idle holder and starts a notice period; the
+//! case is cancelled if the holder becomes active again before it ends, and
+//! only then is the seat released.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use thiserror::Error;
+use uuid::Uuid;
+
+use super::entitlement::{EntitlementManager, SeatEntitlement};
+use super::license::LicenseFeature;
+
+/// Actor recorded on cases resolved by the workflow itself
+pub const SEAT_RECLAMATION_ACTOR: &str = "system:seat-reclamation";
+
+/// Errors that can occur in the reclamation workflow
+#[derive(Debug, Error)]
+pub enum SeatError {
+    #[error("Reclamation case not found: {0}")]
+    CaseNotFound(Uuid),
+
+    #[error("Reclamation case {0} is already closed")]
+    CaseClosed(Uuid),
+}
+
+/// Use of one feature by one seat holder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureUse {
+    /// Feature that was used
+    pub feature: LicenseFeature,
+
+    /// Number of recorded uses
+    pub count: u64,
+
+    /// Most recent use
+    pub last_used: DateTime<Utc>,
+}
+
+/// Recorded activity of one seat holder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeatActivity {
+    /// User identifier
+    pub user_id: String,
+
+    /// When the user was first seen or assigned a seat
+    pub first_seen: DateTime<Utc>,
+
+    /// Most recent activity, if any
+    pub last_active: Option<DateTime<Utc>>,
+
+    /// Number of seat acquisitions
+    pub sessions: u64,
+
+    /// Whether the user currently holds a concurrent seat
+    pub holding: bool,
+
+    /// Days on which the user was active
+    pub active_days: BTreeSet<NaiveDate>,
+
+    /// Feature usage, keyed by feature
+    pub features: HashMap<LicenseFeature, FeatureUse>,
+}
+
+impl SeatActivity {
+    fn new(user_id: String, at: DateTime<Utc>) -> Self {
+        Self {
+            user_id,
+            first_seen: at,
+            last_active: None,
+            sessions: 0,
+            holding: false,
+            active_days: BTreeSet::new(),
+            features: HashMap::new(),
+        }
+    }
+
+    /// Whole days since the last activity, or since the seat was assigned
+    /// when the user was never active
+    pub fn idle_days(&self, now: DateTime<Utc>) -> i64 {
+        now.signed_duration_since(self.last_active.unwrap_or(self.first_seen))
+            .num_days()
+            .max(0)
+    }
+}
+
+/// Highest number of seats held at once
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConcurrencyPeak {
+    /// Seats held
+    pub seats: u32,
+
+    /// When the peak was first reached
+    pub at: DateTime<Utc>,
+}
+
+/// Seat usage recorder
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeatAnalytics {
+    /// Activity per user
+    seats: HashMap<String, SeatActivity>,
+
+    /// Seats currently held
+    concurrent: u32,
+
+    /// Highest concurrency seen on each day
+    daily_peaks: BTreeMap<NaiveDate, u32>,
+
+    /// Highest concurrency seen overall
+    peak: Option<ConcurrencyPeak>,
+}
+
+impl SeatAnalytics {
+    /// Create an empty recorder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that a named seat was assigned, so idle time is counted from
+    /// the assignment rather than from the start of the license
+    pub fn record_assignment(&mut self, user_id: &str, at: DateTime<Utc>) {
+        self.entry(user_id, at);
+    }
+
+    /// Record a seat acquisition
+    pub fn record_acquire(&mut self, user_id: &str, at: DateTime<Utc>) {
+        let activity = self.entry(user_id, at);
+        if activity.holding {
+            self.record_activity(user_id, at);
+            return;
+        }
+        activity.holding = true;
+        activity.sessions += 1;
+        self.concurrent += 1;
+
+        if self.peak.is_none_or(|peak| self.concurrent > peak.seats) {
+            self.peak = Some(ConcurrencyPeak {
+                seats: self.concurrent,
+                at,
+            });
+        }
+        self.record_activity(user_id, at);
+    }
+
+    /// Record a seat release. Releasing is not activity: idle sessions are
+    /// released on the user's behalf.
+    pub fn record_release(&mut self, user_id: &str, at: DateTime<Utc>) {
+        if let Some(activity) = self.seats.get_mut(user_id) {
+            if activity.holding {
+                activity.holding = false;
+                self.concurrent = self.concurrent.saturating_sub(1);
+            }
+        }
+        self.bump_daily_peak(at);
+    }
+
+    /// Record activity by a seat holder
+    pub fn record_activity(&mut self, user_id: &str, at: DateTime<Utc>) {
+        let activity = self.entry(user_id, at);
+        if activity.last_active.is_none_or(|last| at > last) {
+            activity.last_active = Some(at);
+        }
+        activity.active_days.insert(at.date_naive());
+        self.bump_daily_peak(at);
+    }
+
+    /// Record use of a licensed feature
+    pub fn record_feature_use(
+        &mut self,
+        user_id: &str,
+        feature: LicenseFeature,
+        at: DateTime<Utc>,
+    ) {
+        let activity = self.entry(user_id, at);
+        let usage = activity.features.entry(feature).or_insert(FeatureUse {
+            feature,
+            count: 0,
+            last_used: at,
+        });
+        usage.count += 1;
+        if at > usage.last_used {
+            usage.last_used = at;
+        }
+        self.record_activity(user_id, at);
+    }
+
+    /// Activity recorded for a user
+    pub fn activity(&self, user_id: &str) -> Option<&SeatActivity> {
+        self.seats.get(user_id)
+    }
+
+    /// Seats currently held
+    pub fn concurrent(&self) -> u32 {
+        self.concurrent
+    }
+
+    /// Highest concurrency seen overall
+    pub fn peak(&self) -> Option<ConcurrencyPeak> {
+        self.peak
+    }
+
+    /// Daily concurrency peaks within a period
+    pub fn daily_peaks(&self, period: &ReportPeriod) -> Vec<DailyPeak> {
+        self.daily_peaks
+            .range(period.start.date_naive()..=period.end.date_naive())
+            .filter(|(date, _)| period.contains_date(**date))
+            .map(|(date, seats)| DailyPeak {
+                date: *date,
+                seats: *seats,
+            })
+            .collect()
+    }
+
+    /// Build a seat report for `period`
+    pub fn report(
+        &self,
+        seats: &SeatEntitlement,
+        period: ReportPeriod,
+        policy: &ReclamationPolicy,
+        now: DateTime<Utc>,
+    ) -> SeatReport {
+        let daily_peaks = self.daily_peaks(&period);
+        let peak = daily_peaks
+            .iter()
+            .max_by(|a, b| a.seats.cmp(&b.seats).then(b.date.cmp(&a.date)))
+            .cloned();
+
+        let named: BTreeSet<&str> = seats
+            .named_users
+            .iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+
+        // Everyone holding a seat, plus anyone who used one during the period
+        let mut users: BTreeSet<&str> = named.clone();
+        users.extend(seats.active_users.keys().map(String::as_str));
+        users.extend(self.seats.values().filter_map(|activity| {
+            let active_in_period = activity
+                .active_days
+                .iter()
+                .any(|day| period.contains_date(*day));
+            (activity.holding || active_in_period).then_some(activity.user_id.as_str())
+        }));
+
+        let mut usage = Vec::new();
+        let mut suggestions = Vec::new();
+
+        for user_id in users {
+            let holds_seat = named.contains(user_id) || seats.active_users.contains_key(user_id);
+            let activity = self.seats.get(user_id);
+
+            let mut features: Vec<FeatureUse> = activity
+                .map(|a| a.features.values().cloned().collect())
+                .unwrap_or_default();
+            features.sort_by(|a, b| {
+                b.count
+                    .cmp(&a.count)
+                    .then(a.feature.name().cmp(b.feature.name()))
+            });
+
+            let idle_days = activity.map(|a| a.idle_days(now));
+            let state = match (activity.and_then(|a| a.last_active), idle_days) {
+                (None, _) => SeatState::Unused,
+                (Some(_), Some(days)) if days >= policy.idle_days => SeatState::Idle,
+                _ => SeatState::Active,
+            };
+
+            if holds_seat && state != SeatState::Active && !policy.is_exempt(user_id) {
+                let reason = match (state, idle_days) {
+                    (SeatState::Unused, Some(days)) => {
+                        format!("Seat assigned {} days ago and never used", days)
+                    }
+                    (SeatState::Unused, None) => "No recorded activity".to_string(),
+                    (_, days) => format!("No activity for {} days", days.unwrap_or_default()),
+                };
+                if idle_days.is_none_or(|days| days >= policy.idle_days) {
+                    suggestions.push(ReclamationSuggestion {
+                        user_id: user_id.to_string(),
+                        last_active: activity.and_then(|a| a.last_active),
+                        idle_days,
+                        reason,
+                    });
+                }
+            }
+
+            usage.push(SeatUsage {
+                user_id: user_id.to_string(),
+                holds_seat,
+                named: named.contains(user_id),
+                last_active: activity.and_then(|a| a.last_active),
+                idle_days,
+                active_days: activity.map_or(0, |a| {
+                    a.active_days
+                        .iter()
+                        .filter(|day| period.contains_date(**day))
+                        .count() as u32
+                }),
+                sessions: activity.map_or(0, |a| a.sessions),
+                features,
+                state,
+            });
+        }
+
+        let peak_concurrency = peak.as_ref().map_or(0, |day| day.seats);
+        let suggested: BTreeSet<&str> = suggestions.iter().map(|s| s.user_id.as_str()).collect();
+        let kept_named = named
+            .iter()
+            .filter(|user| !suggested.contains(*user))
+            .count() as u32;
+        let demand = peak_concurrency.max(kept_named) as u64;
+        let recommended_seats = seats.max_seats.and_then(|max| {
+            let needed =
+                ((demand * (100 + policy.headroom_percent as u64)).div_ceil(100) as u32).max(1);
+            (needed < max).then_some(needed)
+        });
+
+        SeatReport {
+            generated_at: now,
+            period,
+            max_seats: seats.max_seats,
+            seats_in_use: seats.used_seats,
+            peak_concurrency,
+            peak_day: peak.map(|day| day.date),
+            daily_peaks,
+            seats: usage,
+            suggestions,
+            recommended_seats,
+        }
+    }
+
+    fn entry(&mut self, user_id: &str, at: DateTime<Utc>) -> &mut SeatActivity {
+        self.seats
+            .entry(user_id.to_string())
+            .or_insert_with(|| SeatActivity::new(user_id.to_string(), at))
+    }
+
+    fn bump_daily_peak(&mut self, at: DateTime<Utc>) {
+        let peak = self.daily_peaks.entry(at.date_naive()).or_insert(0);
+        *peak = (*peak).max(self.concurrent);
+    }
+}
+
+/// Half-open reporting window `[start, end)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportPeriod {
+    /// Start of the period (inclusive)
+    pub start: DateTime<Utc>,
+
+    /// End of the period (exclusive)
+    pub end: DateTime<Utc>,
+}
+
+impl ReportPeriod {
+    /// Create a period
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self { start, end }
+    }
+
+    /// The calendar month containing `at`
+    pub fn month_of(at: DateTime<Utc>) -> Self {
+        let start = Utc
+            .with_ymd_and_hms(at.year(), at.month(), 1, 0, 0, 0)
+            .unwrap();
+        let (year, month) = if at.month() == 12 {
+            (at.year() + 1, 1)
+        } else {
+            (at.year(), at.month() + 1)
+        };
+        let end = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).unwrap();
+        Self { start, end }
+    }
+
+    /// The calendar month before the one containing `at`
+    pub fn previous_month(at: DateTime<Utc>) -> Self {
+        Self::month_of(Self::month_of(at).start - Duration::days(1))
+    }
+
+    /// Whether any part of `date` falls inside the period
+    pub fn contains_date(&self, date: NaiveDate) -> bool {
+        let day_start = Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap());
+        day_start < self.end && day_start + Duration::days(1) > self.start
+    }
+}
+
+/// How a seat has been used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SeatState {
+    /// Used within the idle threshold
+    Active,
+
+    /// Not used for at least the idle threshold
+    Idle,
+
+    /// Never used
+    Unused,
+}
+
+/// Highest concurrency on one day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyPeak {
+    /// Day
+    pub date: NaiveDate,
+
+    /// Seats held at the busiest moment
+    pub seats: u32,
+}
+
+/// One seat in a report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeatUsage {
+    /// User identifier
+    pub user_id: String,
+
+    /// Whether the user holds a named or concurrent seat right now
+    pub holds_seat: bool,
+
+    /// Whether the user is on the named user list
+    pub named: bool,
+
+    /// Most recent activity
+    pub last_active: Option<DateTime<Utc>>,
+
+    /// Days since the last activity (or assignment)
+    pub idle_days: Option<i64>,
+
+    /// Days with activity inside the report period
+    pub active_days: u32,
+
+    /// Seat acquisitions to date
+    pub sessions: u64,
+
+    /// Features used, most used first
+    pub features: Vec<FeatureUse>,
+
+    /// Usage state
+    pub state: SeatState,
+}
+
+/// A seat that could be reclaimed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReclamationSuggestion {
+    /// Seat holder
+    pub user_id: String,
+
+    /// Most recent activity
+    pub last_active: Option<DateTime<Utc>>,
+
+    /// Days since the last activity (or assignment)
+    pub idle_days: Option<i64>,
+
+    /// Why the seat is suggested
+    pub reason: String,
+}
+
+/// Seat usage report for one period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeatReport {
+    /// When the report was built
+    pub generated_at: DateTime<Utc>,
+
+    /// Period covered
+    pub period: ReportPeriod,
+
+    /// Licensed seats (None = unlimited)
+    pub max_seats: Option<u32>,
+
+    /// Concurrent seats held when the report was built
+    pub seats_in_use: u32,
+
+    /// Highest concurrency during the period
+    pub peak_concurrency: u32,
+
+    /// Day the peak was reached
+    pub peak_day: Option<NaiveDate>,
+
+    /// Highest concurrency on each day with activity
+    pub daily_peaks: Vec<DailyPeak>,
+
+    /// Per-seat usage
+    pub seats: Vec<SeatUsage>,
+
+    /// Idle or unused seats that could be reclaimed
+    pub suggestions: Vec<ReclamationSuggestion>,
+
+    /// Smaller seat count that would still cover observed demand
+    pub recommended_seats: Option<u32>,
+}
+
+impl SeatReport {
+    /// Plain-text summary for emailed reports
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!(
+            "Seat usage {} to {}",
+            self.period.start.format("%Y-%m-%d"),
+            (self.period.end - Duration::days(1)).format("%Y-%m-%d")
+        )];
+
+        let licensed = self
+            .max_seats
+            .map_or_else(|| "unlimited".to_string(), |max| max.to_string());
+        lines.push(format!(
+            "Licensed seats: {}, in use now: {}, peak: {}{}",
+            licensed,
+            self.seats_in_use,
+            self.peak_concurrency,
+            self.peak_day
+                .map(|day| format!(" on {}", day))
+                .unwrap_or_default()
+        ));
+
+        let count = |state| self.seats.iter().filter(|seat| seat.state == state).count();
+        lines.push(format!(
+            "Active: {}, idle: {}, never used: {}",
+            count(SeatState::Active),
+            count(SeatState::Idle),
+            count(SeatState::Unused)
+        ));
+
+        if !self.suggestions.is_empty() {
+            lines.push(String::new());
+            lines.push("Suggested for reclamation:".to_string());
+            for suggestion in &self.suggestions {
+                lines.push(format!("- {}: {}", suggestion.user_id, suggestion.reason));
+            }
+        }
+
+        if let Some(recommended) = self.recommended_seats {
+            lines.push(String::new());
+            lines.push(format!(
+                "Observed demand fits in {} seats; consider reducing the license at renewal.",
+                recommended
+            ));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// When seats count as idle and how reclamation proceeds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReclamationPolicy {
+    /// Days without activity before a seat is suggested
+    pub idle_days: i64,
+
+    /// Days between notifying the holder and releasing the seat
+    pub notice_days: i64,
+
+    /// Users whose seats are never suggested
+    pub exempt_users: Vec<String>,
+
+    /// Headroom above observed demand when recommending a seat count
+    pub headroom_percent: u32,
+}
+
+impl ReclamationPolicy {
+    /// Whether a user's seat is exempt from reclamation
+    pub fn is_exempt(&self, user_id: &str) -> bool {
+        self.exempt_users.iter().any(|user| user == user_id)
+    }
+}
+
+impl Default for ReclamationPolicy {
+    fn default() -> Self {
+        Self {
+            idle_days: 30,
+            notice_days: 7,
+            exempt_users: Vec::new(),
+            headroom_percent: 10,
+        }
+    }
+}
+
+/// Reclamation case status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CaseStatus {
+    /// Holder notified, notice period running
+    Notified,
+
+    /// Seat released
+    Reclaimed,
+
+    /// Holder became active again
+    Cancelled,
+
+    /// An administrator chose to keep the seat
+    Dismissed,
+}
+
+/// Reclamation of one idle seat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReclamationCase {
+    /// Case identifier
+    pub id: Uuid,
+
+    /// Seat holder
+    pub user_id: String,
+
+    /// Current status
+    pub status: CaseStatus,
+
+    /// Why the seat was suggested
+    pub reason: String,
+
+    /// Holder's last activity when the case was opened
+    pub last_active: Option<DateTime<Utc>>,
+
+    /// When the case was opened and the holder notified
+    pub opened_at: DateTime<Utc>,
+
+    /// When the seat is released unless the holder becomes active
+    pub reclaim_after: DateTime<Utc>,
+
+    /// When the case was closed
+    pub resolved_at: Option<DateTime<Utc>>,
+
+    /// Who closed the case
+    pub resolved_by: Option<String>,
+
+    /// Note recorded on closing
+    pub note: Option<String>,
+}
+
+impl ReclamationCase {
+    /// Whether the case is still waiting on its notice period
+    pub fn is_open(&self) -> bool {
+        self.status == CaseStatus::Notified
+    }
+
+    fn close(&mut self, status: CaseStatus, by: &str, note: Option<String>, at: DateTime<Utc>) {
+        self.status = status;
+        self.resolved_at = Some(at);
+        self.resolved_by = Some(by.to_string());
+        self.note = note;
+    }
+}
+
+/// Seat entitlements together with their reclamation workflow
+pub struct SeatManager {
+    /// Seat entitlements and recorded usage
+    pub entitlements: EntitlementManager,
+
+    /// Reclamation policy
+    pub policy: ReclamationPolicy,
+
+    /// All reclamation cases, oldest first
+    cases: Vec<ReclamationCase>,
+}
+
+impl SeatManager {
+    /// Create a manager over existing entitlements
+    pub fn new(entitlements: EntitlementManager, policy: ReclamationPolicy) -> Self {
+        Self {
+            entitlements,
+            policy,
+            cases: Vec::new(),
+        }
+    }
+
+    /// Build a seat report for `period`
+    pub fn report(&self, period: ReportPeriod, now: DateTime<Utc>) -> SeatReport {
+        self.entitlements
+            .analytics
+            .report(&self.entitlements.seats, period, &self.policy, now)
+    }
+
+    /// All reclamation cases, oldest first
+    pub fn cases(&self) -> &[ReclamationCase] {
+        &self.cases
+    }
+
+    /// Cases still in their notice period
+    pub fn open_cases(&self) -> Vec<&ReclamationCase> {
+        self.cases.iter().filter(|case| case.is_open()).collect()
+    }
+
+    /// Open cases for idle seats that have none yet. Seats whose last case
+    /// was dismissed are left alone for another idle period. Returns the new
+    /// cases so their holders can be notified.
+    pub fn review(&mut self, now: DateTime<Utc>) -> Vec<ReclamationCase> {
+        let report = self.report(ReportPeriod::month_of(now), now);
+        let mut opened = Vec::new();
+
+        for suggestion in report.suggestions {
+            let latest = self
+                .cases
+                .iter()
+                .rev()
+                .find(|case| case.user_id == suggestion.user_id);
+            let skip = latest.is_some_and(|case| {
+                case.is_open()
+                    || (case.status == CaseStatus::Dismissed
+                        && case
+                            .resolved_at
+                            .is_some_and(|at| now < at + Duration::days(self.policy.idle_days)))
+            });
+            if skip {
+                continue;
+            }
+
+            let case = ReclamationCase {
+                id: Uuid::new_v4(),
+                user_id: suggestion.user_id,
+                status: CaseStatus::Notified,
+                reason: suggestion.reason,
+                last_active: suggestion.last_active,
+                opened_at: now,
+                reclaim_after: now + Duration::days(self.policy.notice_days),
+                resolved_at: None,
+                resolved_by: None,
+                note: None,
+            };
+            self.cases.push(case.clone());
+            opened.push(case);
+        }
+
+        opened
+    }
+
+    /// Close open cases whose holder became active again and reclaim those
+    /// whose notice period has ended. Returns the cases closed.
+    pub fn advance(&mut self, now: DateTime<Utc>) -> Vec<ReclamationCase> {
+        let mut closed = Vec::new();
+
+        for index in 0..self.cases.len() {
+            let case = &self.cases[index];
+            if !case.is_open() {
+                continue;
+            }
+
+            let returned = self
+                .entitlements
+                .analytics
+                .activity(&case.user_id)
+                .and_then(|activity| activity.last_active)
+                .is_some_and(|last| last > case.opened_at);
+
+            if returned {
+                self.cases[index].close(
+                    CaseStatus::Cancelled,
+                    SEAT_RECLAMATION_ACTOR,
+                    Some("Seat holder became active again".to_string()),
+                    now,
+                );
+            } else if now >= case.reclaim_after {
+                let user_id = case.user_id.clone();
+                self.entitlements.reclaim_seat(&user_id);
+                self.cases[index].close(
+                    CaseStatus::Reclaimed,
+                    SEAT_RECLAMATION_ACTOR,
+                    Some("Notice period ended".to_string()),
+                    now,
+                );
+            } else {
+                continue;
+            }
+            closed.push(self.cases[index].clone());
+        }
+
+        closed
+    }
+
+    /// Reclaim a seat now without waiting for the notice period
+    pub fn reclaim(
+        &mut self,
+        case_id: Uuid,
+        by: &str,
+        now: DateTime<Utc>,
+    ) -> Result<ReclamationCase, SeatError> {
+        let case = self.open_case_mut(case_id)?;
+        let user_id = case.user_id.clone();
+        case.close(CaseStatus::Reclaimed, by, None, now);
+        let case = case.clone();
+        self.entitlements.reclaim_seat(&user_id);
+        Ok(case)
+    }
+
+    /// Keep the seat and close the case
+    pub fn dismiss(
+        &mut self,
+        case_id: Uuid,
+        by: &str,
+        note: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Result<ReclamationCase, SeatError> {
+        let case = self.open_case_mut(case_id)?;
+        case.close(CaseStatus::Dismissed, by, note, now);
+        Ok(case.clone())
+    }
+
+    fn open_case_mut(&mut self, case_id: Uuid) -> Result<&mut ReclamationCase, SeatError> {
+        let case = self
+            .cases
+            .iter_mut()
+            .find(|case| case.id == case_id)
+            .ok_or(SeatError::CaseNotFound(case_id))?;
+        if !case.is_open() {
+            return Err(SeatError::CaseClosed(case_id));
+        }
+        Ok(case)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enterprise::licensing::license::{License, LicenseType, LicenseeInfo};
+
+    fn manager(max_users: u32) -> SeatManager {
+        let licensee = LicenseeInfo {
+            name: "Test User".to_string(),
+            email: "test@example.com".to_string(),
+            organization: None,
+            country: None,
+        };
+        let mut license = License::new(
+            "TEST-KEY-123".to_string(),
+            LicenseType::Professional,
+            licensee,
+        );
+        license.limits.max_users = Some(max_users);
+        SeatManager::new(
+            EntitlementManager::new(&license),
+            ReclamationPolicy::default(),
+        )
+    }
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, d, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_activity_and_peaks() {
+        let mut analytics = SeatAnalytics::new();
+        analytics.record_acquire("alice", day(1));
+        analytics.record_acquire("bob", day(1));
+        analytics.record_feature_use("alice", LicenseFeature::Advanced3D, day(2));
+        analytics.record_feature_use("alice", LicenseFeature::Advanced3D, day(3));
+        analytics.record_release("bob", day(3));
+        analytics.record_acquire("carol", day(4));
+
+        let alice = analytics.activity("alice").unwrap();
+        assert_eq!(alice.last_active, Some(day(3)));
+        assert_eq!(alice.active_days.len(), 3);
+        assert_eq!(alice.features[&LicenseFeature::Advanced3D].count, 2);
+
+        assert_eq!(analytics.concurrent(), 2);
+        assert_eq!(analytics.peak().unwrap().seats, 2);
+
+        let period = ReportPeriod::month_of(day(15));
+        let peaks: Vec<u32> = analytics
+            .daily_peaks(&period)
+            .iter()
+            .map(|p| p.seats)
+            .collect();
+        assert_eq!(peaks, vec![2, 2, 2, 2]);
+
+        let previous = ReportPeriod::previous_month(day(15));
+        assert_eq!(
+            previous.start,
+            Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap()
+        );
+        assert!(analytics.daily_peaks(&previous).is_empty());
+    }
+
+    #[test]
+    fn test_report_suggests_idle_seats() {
+        let mut seats = manager(10);
+        seats.entitlements.seats.named_users = Some(vec![
+            "alice".to_string(),
+            "bob".to_string(),
+            "carol".to_string(),
+        ]);
+        seats
+            .entitlements
+            .analytics
+            .record_assignment("carol", day(1));
+        seats
+            .entitlements
+            .analytics
+            .record_activity("alice", day(1));
+        seats.entitlements.analytics.record_activity("bob", day(1));
+        seats.policy.exempt_users.push("bob".to_string());
+
+        let now = day(1) + Duration::days(40);
+        let report = seats.report(ReportPeriod::month_of(now), now);
+
+        let states: Vec<(&str, SeatState)> = report
+            .seats
+            .iter()
+            .map(|seat| (seat.user_id.as_str(), seat.state))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                ("alice", SeatState::Idle),
+                ("bob", SeatState::Idle),
+                ("carol", SeatState::Unused),
+            ]
+        );
+
+        let suggested: Vec<&str> = report
+            .suggestions
+            .iter()
+            .map(|s| s.user_id.as_str())
+            .collect();
+        assert_eq!(suggested, vec!["alice", "carol"]);
+        assert_eq!(report.recommended_seats, Some(2));
+        assert!(report
+            .summary()
+            .contains("- carol: Seat assigned 40 days ago and never used"));
+    }
+
+    #[test]
+    fn test_reclamation_workflow() {
+        let mut seats = manager(10);
+        seats.entitlements.seats.named_users = Some(vec![
+            "alice".to_string(),
+            "bob".to_string(),
+            "carol".to_string(),
+        ]);
+        for user in ["alice", "bob", "carol"] {
+            seats.entitlements.analytics.record_activity(user, day(1));
+        }
+
+        let now = day(1) + Duration::days(31);
+        let opened = seats.review(now);
+        assert_eq!(opened.len(), 3);
+        assert!(seats.review(now).is_empty());
+
+        // Bob comes back during the notice period, carol's seat is kept
+        seats
+            .entitlements
+            .analytics
+            .record_activity("bob", now + Duration::days(1));
+        let carol = opened.iter().find(|case| case.user_id == "carol").unwrap();
+        seats
+            .dismiss(carol.id, "admin", Some("On leave".to_string()), now)
+            .unwrap();
+        assert!(matches!(
+            seats.dismiss(carol.id, "admin", None, now),
+            Err(SeatError::CaseClosed(_))
+        ));
+
+        assert!(seats.advance(now + Duration::days(2)).len() == 1);
+        let closed = seats.advance(now + Duration::days(8));
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].user_id, "alice");
+        assert_eq!(closed[0].status, CaseStatus::Reclaimed);
+        assert_eq!(
+            seats.entitlements.seats.named_users,
+            Some(vec!["bob".to_string(), "carol".to_string()])
+        );
+
+        // Dismissed seats stay out of review for another idle period
+        assert!(seats.review(now + Duration::days(10)).is_empty());
+        assert_eq!(seats.review(now + Duration::days(30)).len(), 1);
+    }
+}
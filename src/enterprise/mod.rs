@@ -63,6 +63,12 @@
 //!   aggregates, commands, projections, snapshots, event replay/upcasting, and sagas for
 //!   long-running processes with compensation actions.
 //!
+//! ### Connectivity
+//!
+//! - **Connection Resiliency** (`resilience`): Shared exponential backoff with jitter,
+//!   offline detection and probing, hedged idempotent reads, and a watchable connection
+//!   status for collaboration, cloud storage, and API clients.
+//!
 //! ## Architecture Overview
 //!
 //! The enterprise modules are designed to be:
@@ -251,7 +257,8 @@ pub mod graphql;
 ///
 /// Complete event sourcing and Command Query Responsibility Segregation (CQRS)
 /// implementation with event store, aggregates, commands, projections, snapshots,
-/// event replay, and saga/process manager support.
+/// event replay, saga/process manager support, and a schema registry for
+/// event payloads.
 pub mod eventsource;
 
 /// Distributed tracing and observability
@@ -268,6 +275,13 @@ pub mod tracing;
 /// throttling policies (reject, delay, degrade, priority queue), and analytics with abuse detection.
 pub mod ratelimit;
 
+/// Connection resiliency
+///
+/// Retry, backoff, request hedging and offline detection shared by the
+/// collaboration transport, cloud storage and API clients, with a
+/// user-visible connection status.
+pub mod resilience;
+
 // ============================================================================
 // Common Enterprise Types & Utilities
 // ============================================================================
@@ -8,6 +8,7 @@ use thiserror::Error;
 use uuid::Uuid;
 
 use super::ot::Operation;
+use crate::commands::{ChangeSet, Document};
 use super::presence::{CursorPosition, Selection, UserStatus};
 
 /// Errors related to synchronization
@@ -128,6 +129,13 @@ pub enum SyncMessage {
         since_version: u64,
     },
 
+    /// Entities changed in the room's drawing, answering `RequestDelta` or
+    /// pushed as the drawing changes
+    EntityChanges {
+        room_id: String,
+        changes: ChangeSet,
+    },
+
     /// Error message
     Error {
         code: String,
@@ -179,12 +187,22 @@ impl SyncMessage {
             | SyncMessage::RequestSync { room_id, .. }
             | SyncMessage::FullSync { room_id, .. }
             | SyncMessage::RequestDelta { room_id, .. }
+            | SyncMessage::EntityChanges { room_id, .. }
             | SyncMessage::UserJoined { room_id, .. }
             | SyncMessage::UserLeft { room_id, .. } => Some(room_id),
             _ => None,
         }
     }
 
+    /// Answer a `RequestDelta` with the entities changed in `document` since the
+    /// client's version
+    pub fn entity_delta(room_id: String, document: &Document, since_version: u64) -> Self {
+        SyncMessage::EntityChanges {
+            room_id,
+            changes: document.change_set(since_version),
+        }
+    }
+
     /// Serialize to JSON
     pub fn to_json(&self) -> Result<String, SyncError> {
         serde_json::to_string(self)
@@ -221,6 +239,8 @@ pub struct DeltaSync {
     pending_ops: Vec<Operation>,
     /// Operations waiting for server acknowledgment
     inflight_ops: HashMap<Uuid, Operation>,
+    /// Drawing version whose entity changes have already been sent
+    entities_version: u64,
 }
 
 impl DeltaSync {
@@ -231,6 +251,7 @@ impl DeltaSync {
             current_version: initial_version,
             pending_ops: Vec::new(),
             inflight_ops: HashMap::new(),
+            entities_version: 0,
         }
     }
 
@@ -278,6 +299,20 @@ impl DeltaSync {
     pub fn has_pending(&self) -> bool {
         !self.pending_ops.is_empty()
     }
+
+    /// Entities changed in `document` since the last call, or `None` if nothing
+    /// changed
+    pub fn entity_changes(&mut self, document: &Document) -> Option<SyncMessage> {
+        let changes = document.change_set(self.entities_version);
+        self.entities_version = changes.version;
+        if changes.changes.is_empty() {
+            return None;
+        }
+        Some(SyncMessage::EntityChanges {
+            room_id: self.room_id.clone(),
+            changes,
+        })
+    }
 }
 
 /// Compression utilities
@@ -493,6 +528,33 @@ mod tests {
         assert_eq!(delta.pending_count(), 0);
     }
 
+    #[test]
+    fn test_entity_changes() {
+        let mut document = Document::new();
+        let mut delta = DeltaSync::new("room1".to_string(), 0);
+        assert!(delta.entity_changes(&document).is_none());
+
+        let a = document.add_entity(Box::new(1u32));
+        let b = document.add_entity(Box::new(2u32));
+        let Some(SyncMessage::EntityChanges { changes, .. }) = delta.entity_changes(&document) else {
+            panic!("expected entity changes");
+        };
+        assert_eq!(changes.changes.len(), 2);
+        assert!(delta.entity_changes(&document).is_none());
+
+        // A client that saw `a` asks for what it missed
+        document.remove_entity(&a);
+        let reply = SyncMessage::entity_delta("room1".to_string(), &document, 1);
+        let json = reply.to_json().unwrap();
+        let Ok(SyncMessage::EntityChanges { changes, .. }) = SyncMessage::from_json(&json) else {
+            panic!("expected entity changes");
+        };
+        let ids: Vec<_> = changes.changes.iter().map(|change| change.id).collect();
+        assert_eq!(ids, vec![b, a]);
+        assert!(changes.changes[1].revision.deleted);
+        assert_eq!(changes.version, document.version());
+    }
+
     #[test]
     fn test_message_queue() {
         let mut queue = MessageQueue::new(10);
@@ -0,0 +1,122 @@
+//! Exponential backoff with jitter
+//!
+//! A [`BackoffPolicy`] gives the wait before each retry: the initial delay
+//! grown by the multiplier per retry and capped at the maximum, then
+//! shortened by a random fraction of up to `jitter`. Jitter spreads out
+//! clients that lost their connection at the same moment, so they don't
+//! all come back at the same moment too.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Retry timing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackoffPolicy {
+    /// Wait before the first retry
+    pub initial_delay: Duration,
+    /// Longest wait between attempts
+    pub max_delay: Duration,
+    /// Growth of the wait per retry
+    pub multiplier: f64,
+    /// Largest fraction of the wait removed at random, 0.0 - 1.0; 1.0 is
+    /// "full jitter"
+    pub jitter: f64,
+    /// Attempts in total, including the first; `None` keeps trying
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for BackoffPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.5,
+            max_attempts: Some(5),
+        }
+    }
+}
+
+impl BackoffPolicy {
+    /// Policy without jitter, for deterministic schedules
+    pub fn exponential(initial_delay: Duration, max_delay: Duration, multiplier: f64) -> Self {
+        Self {
+            initial_delay,
+            max_delay,
+            multiplier,
+            jitter: 0.0,
+            max_attempts: None,
+        }
+    }
+
+    /// Set the jitter fraction
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set the attempt limit
+    pub fn with_max_attempts(mut self, max_attempts: Option<u32>) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Whether another attempt may follow `attempts` made so far
+    pub fn should_retry(&self, attempts: u32) -> bool {
+        self.max_attempts.is_none_or(|max| attempts < max)
+    }
+
+    /// Wait before retry number `retry` (0 for the first retry) without
+    /// jitter
+    pub fn base_delay(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.max(1.0).powi(retry.min(i32::MAX as u32) as i32);
+        let secs = self.initial_delay.as_secs_f64() * factor;
+        if secs.is_finite() && secs < self.max_delay.as_secs_f64() {
+            Duration::from_secs_f64(secs)
+        } else {
+            self.max_delay
+        }
+    }
+
+    /// Wait before retry number `retry`, with random jitter
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.delay_with(retry, rand::random::<f64>())
+    }
+
+    /// Wait before retry number `retry`, with `unit` in [0, 1) standing in
+    /// for the random draw
+    pub fn delay_with(&self, retry: u32, unit: f64) -> Duration {
+        let jitter = self.jitter.clamp(0.0, 1.0) * unit.clamp(0.0, 1.0);
+        self.base_delay(retry).mul_f64(1.0 - jitter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delays_grow_cap_and_jitter_down() {
+        let policy = BackoffPolicy::exponential(Duration::from_millis(100), Duration::from_secs(1), 2.0);
+        let delays: Vec<_> = (0..6).map(|r| policy.delay(r).as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+        assert_eq!(policy.base_delay(u32::MAX), Duration::from_secs(1));
+
+        let jittered = policy.with_jitter(0.5);
+        assert_eq!(jittered.delay_with(2, 0.0), Duration::from_millis(400));
+        assert_eq!(jittered.delay_with(2, 1.0), Duration::from_millis(200));
+        for _ in 0..100 {
+            let d = jittered.delay(3);
+            assert!(d >= Duration::from_millis(400) && d <= Duration::from_millis(800));
+        }
+    }
+
+    #[test]
+    fn test_attempt_limit() {
+        let policy = BackoffPolicy::default().with_max_attempts(Some(3));
+        assert!(policy.should_retry(1));
+        assert!(policy.should_retry(2));
+        assert!(!policy.should_retry(3));
+        assert!(BackoffPolicy::default().with_max_attempts(None).should_retry(1000));
+    }
+}
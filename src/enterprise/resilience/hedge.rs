@@ -0,0 +1,79 @@
+//! Request hedging for idempotent reads
+//!
+//! A slow read is often stuck behind one bad connection or one overloaded
+//! server rather than slow everywhere. Hedging sends a second copy of the
+//! request when the first hasn't answered within a delay, and takes
+//! whichever answer arrives first; the other is dropped. Only requests
+//! that are safe to repeat may be hedged.
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::future::Future;
+use std::time::Duration;
+
+/// Run `op`, starting another copy each time `after` passes without an
+/// answer, up to `max_hedges` extra copies. Returns the first success, or
+/// the last failure once every copy has failed.
+pub async fn hedge<T, E, F, Fut>(op: &F, after: Duration, max_hedges: usize) -> Result<T, E>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut pending = FuturesUnordered::new();
+    pending.push(op());
+    let mut hedges = 0;
+
+    loop {
+        let timer = tokio::time::sleep(after);
+        tokio::pin!(timer);
+
+        tokio::select! {
+            Some(result) = pending.next() => match result {
+                Ok(value) => return Ok(value),
+                Err(err) if pending.is_empty() => return Err(err),
+                Err(_) => {}
+            },
+            _ = &mut timer, if hedges < max_hedges => {
+                hedges += 1;
+                pending.push(op());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_slow_request_is_hedged() {
+        let calls = AtomicUsize::new(0);
+        let op = || {
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                // The first copy hangs; the hedge answers quickly
+                let wait = if call == 0 { 5_000 } else { 5 };
+                tokio::time::sleep(Duration::from_millis(wait)).await;
+                Ok::<_, String>(call)
+            }
+        };
+
+        let started = std::time::Instant::now();
+        let result = hedge(&op, Duration::from_millis(20), 1).await;
+        assert_eq!(result, Ok(1));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_fast_request_is_not_hedged() {
+        let calls = AtomicUsize::new(0);
+        let op = || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err::<u32, _>("refused") }
+        };
+
+        assert_eq!(hedge(&op, Duration::from_millis(50), 2).await, Err("refused"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,380 @@
+//! # Connection Resiliency
+//!
+//! One retry and reconnection layer shared by collaboration, cloud storage
+//! and API clients, so they all back off, give up and report outages the
+//! same way:
+//!
+//! - **Backoff** ([`backoff`]): exponential delays with jitter and an attempt
+//!   limit
+//! - **Hedging** ([`hedge`]): a second copy of a slow idempotent read, first
+//!   answer wins
+//! - **Offline detection** ([`status`]): services that keep failing are taken
+//!   offline and probed until they answer, and the OS network signal is
+//!   honoured
+//! - **Connection status**: a watchable [`ConnectionStatus`] with a message
+//!   for the status bar
+//!
+//! Whether a failed call is retried depends on what went wrong and on
+//! whether the call is safe to repeat. Errors say which of the three
+//! [`FailureKind`]s they are through [`Classify`]: a call that never
+//! reached the server is always retried, one that may have been processed
+//! is only retried if it is idempotent, and a refusal from the server is
+//! never retried.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use caddy::enterprise::resilience::{Resilience, ResilienceConfig, CLOUD_STORAGE};
+//!
+//! let resilience = Resilience::new(ResilienceConfig::default());
+//! let mut status = resilience.monitor().subscribe();
+//!
+//! let data = resilience
+//!     .read(CLOUD_STORAGE, || storage.download_file("drawings/plan.cdy"))
+//!     .await?;
+//!
+//! println!("{}", status.borrow_and_update().message());
+//! ```
+
+pub mod backoff;
+pub mod hedge;
+pub mod status;
+
+pub use backoff::BackoffPolicy;
+pub use hedge::hedge;
+pub use status::{Admission, ConnectionStatus, ConnectivityMonitor, ServiceState, ServiceStatus};
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Service name for the real-time collaboration connection
+pub const COLLABORATION: &str = "collaboration";
+/// Service name for cloud document storage
+pub const CLOUD_STORAGE: &str = "cloud-storage";
+/// Service name for the REST API
+pub const API: &str = "api";
+
+/// How a call failed, which decides whether it may be retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FailureKind {
+    /// The request never reached the server (refused, unreachable, DNS);
+    /// always safe to retry
+    Unreachable,
+    /// The request may have been processed (timeout, reset, 5xx); safe to
+    /// retry only when idempotent
+    Transient,
+    /// The server answered with a refusal (4xx, not found, denied); retrying
+    /// won't help
+    Permanent,
+}
+
+/// Errors that know which [`FailureKind`] they are
+pub trait Classify {
+    /// Kind of failure
+    fn failure_kind(&self) -> FailureKind;
+}
+
+impl Classify for std::io::Error {
+    fn failure_kind(&self) -> FailureKind {
+        use std::io::ErrorKind;
+        match self.kind() {
+            ErrorKind::ConnectionRefused | ErrorKind::NotConnected | ErrorKind::AddrNotAvailable => {
+                FailureKind::Unreachable
+            }
+            ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe
+            | ErrorKind::TimedOut
+            | ErrorKind::Interrupted
+            | ErrorKind::UnexpectedEof
+            | ErrorKind::WouldBlock => FailureKind::Transient,
+            _ => FailureKind::Permanent,
+        }
+    }
+}
+
+/// Whether a call is safe to repeat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Idempotency {
+    /// Repeating the call has no further effect (reads, PUT of a whole
+    /// object); retried on any transient failure, and hedged
+    Idempotent,
+    /// Repeating the call could apply it twice (appends, POST); retried
+    /// only when it never reached the server
+    NonIdempotent,
+}
+
+/// Failure of a call made through the resiliency layer
+#[derive(Debug, Error)]
+pub enum ResilienceError<E> {
+    /// The service is offline and the call was not attempted
+    #[error("{service} is offline; next attempt in {retry_in:?}")]
+    Offline {
+        /// Service name
+        service: String,
+        /// Time until the service is probed again
+        retry_in: Duration,
+    },
+
+    /// The call failed, after any retries
+    #[error("{0}")]
+    Failed(E),
+}
+
+impl<E> ResilienceError<E> {
+    /// The call's own error, if it was attempted
+    pub fn into_inner(self) -> Option<E> {
+        match self {
+            Self::Failed(err) => Some(err),
+            Self::Offline { .. } => None,
+        }
+    }
+}
+
+/// Resiliency settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResilienceConfig {
+    /// Retry schedule for failed calls
+    pub retry: BackoffPolicy,
+    /// Send a hedged copy of an idempotent read after this long without an
+    /// answer; `None` disables hedging
+    pub hedge_after: Option<Duration>,
+    /// Most hedged copies per read
+    pub max_hedges: usize,
+    /// Consecutive failures before a service counts as offline
+    pub offline_after: u32,
+    /// Probe schedule while a service is offline
+    pub probe: BackoffPolicy,
+}
+
+impl Default for ResilienceConfig {
+    fn default() -> Self {
+        Self {
+            retry: BackoffPolicy::default(),
+            hedge_after: Some(Duration::from_millis(300)),
+            max_hedges: 1,
+            offline_after: 3,
+            probe: BackoffPolicy::exponential(Duration::from_secs(1), Duration::from_secs(60), 2.0).with_jitter(0.2),
+        }
+    }
+}
+
+/// Retries, hedges and tracks calls to remote services
+#[derive(Debug)]
+pub struct Resilience {
+    config: ResilienceConfig,
+    monitor: Arc<ConnectivityMonitor>,
+}
+
+impl Resilience {
+    /// Create a resiliency layer with its own connectivity monitor
+    pub fn new(config: ResilienceConfig) -> Self {
+        let monitor = Arc::new(ConnectivityMonitor::new(config.offline_after, config.probe.clone()));
+        Self { config, monitor }
+    }
+
+    /// Create a resiliency layer reporting to a shared monitor, so one
+    /// status covers every client
+    pub fn with_monitor(config: ResilienceConfig, monitor: Arc<ConnectivityMonitor>) -> Self {
+        Self { config, monitor }
+    }
+
+    /// Settings
+    pub fn config(&self) -> &ResilienceConfig {
+        &self.config
+    }
+
+    /// Connectivity monitor the calls report to
+    pub fn monitor(&self) -> &Arc<ConnectivityMonitor> {
+        &self.monitor
+    }
+
+    /// Current connection status
+    pub fn status(&self) -> ConnectionStatus {
+        self.monitor.status()
+    }
+
+    /// Make an idempotent call: retried on any transient failure and
+    /// hedged when slow
+    pub async fn read<T, E, F, Fut>(&self, service: &str, op: F) -> Result<T, ResilienceError<E>>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Classify + std::fmt::Display,
+    {
+        self.execute(service, Idempotency::Idempotent, op).await
+    }
+
+    /// Make a call that must not be applied twice: retried only when it
+    /// never reached the server
+    pub async fn write<T, E, F, Fut>(&self, service: &str, op: F) -> Result<T, ResilienceError<E>>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Classify + std::fmt::Display,
+    {
+        self.execute(service, Idempotency::NonIdempotent, op).await
+    }
+
+    /// Make a call to `service`
+    pub async fn execute<T, E, F, Fut>(
+        &self,
+        service: &str,
+        idempotency: Idempotency,
+        op: F,
+    ) -> Result<T, ResilienceError<E>>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Classify + std::fmt::Display,
+    {
+        let mut attempts = 0;
+        loop {
+            let admission = self.monitor.admit(service);
+            if let Admission::Offline { retry_in } = admission {
+                return Err(ResilienceError::Offline {
+                    service: service.to_string(),
+                    retry_in,
+                });
+            }
+
+            attempts += 1;
+            let started = Instant::now();
+            let result = match (idempotency, self.config.hedge_after) {
+                (Idempotency::Idempotent, Some(after)) if self.config.max_hedges > 0 => {
+                    hedge(&op, after, self.config.max_hedges).await
+                }
+                _ => op().await,
+            };
+
+            let err = match result {
+                Ok(value) => {
+                    self.monitor.record_success(service, started.elapsed());
+                    return Ok(value);
+                }
+                Err(err) => err,
+            };
+
+            let retry = match err.failure_kind() {
+                FailureKind::Permanent => {
+                    // The server answered, so it is reachable
+                    self.monitor.record_success(service, started.elapsed());
+                    false
+                }
+                FailureKind::Unreachable => {
+                    self.monitor.record_failure(service, &err.to_string());
+                    true
+                }
+                FailureKind::Transient => {
+                    self.monitor.record_failure(service, &err.to_string());
+                    idempotency == Idempotency::Idempotent
+                }
+            };
+
+            // A failed probe leaves the next attempt to the probe schedule
+            if !retry
+                || admission == Admission::Probe
+                || self.monitor.is_offline(service)
+                || !self.config.retry.should_retry(attempts)
+            {
+                return Err(ResilienceError::Failed(err));
+            }
+
+            let delay = self.config.retry.delay(attempts - 1);
+            log::debug!("{} call failed ({}), retry {} in {:?}", service, err, attempts, delay);
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+impl Default for Resilience {
+    fn default() -> Self {
+        Self::new(ResilienceConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Error, ErrorKind};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn resilience(offline_after: u32) -> Resilience {
+        Resilience::new(ResilienceConfig {
+            retry: BackoffPolicy::exponential(Duration::from_millis(1), Duration::from_millis(5), 2.0)
+                .with_max_attempts(Some(4)),
+            hedge_after: None,
+            offline_after,
+            probe: BackoffPolicy::exponential(Duration::from_secs(60), Duration::from_secs(60), 1.0),
+            ..ResilienceConfig::default()
+        })
+    }
+
+    /// Call that fails with `kind` until the `succeed_on`-th attempt
+    async fn flaky(calls: &AtomicU32, kind: ErrorKind, succeed_on: u32) -> Result<u32, Error> {
+        let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+        if call >= succeed_on {
+            Ok(call)
+        } else {
+            Err(Error::new(kind, "flaky"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_depend_on_idempotency() {
+        let resilience = resilience(10);
+
+        let calls = AtomicU32::new(0);
+        let result = resilience.read(API, || flaky(&calls, ErrorKind::TimedOut, 3)).await;
+        assert_eq!(result.unwrap(), 3);
+
+        // A timed-out write may have been applied, so it isn't repeated
+        let calls = AtomicU32::new(0);
+        let result = resilience.write(API, || flaky(&calls, ErrorKind::TimedOut, 3)).await;
+        assert!(matches!(result, Err(ResilienceError::Failed(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // A refused write never arrived, so it is
+        let calls = AtomicU32::new(0);
+        let result = resilience
+            .write(API, || flaky(&calls, ErrorKind::ConnectionRefused, 2))
+            .await;
+        assert_eq!(result.unwrap(), 2);
+
+        // Permanent failures are returned at once
+        let calls = AtomicU32::new(0);
+        let result = resilience
+            .read(API, || flaky(&calls, ErrorKind::PermissionDenied, 2))
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(resilience.status().state(), ServiceState::Online);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_failures_go_offline_and_fail_fast() {
+        let resilience = resilience(2);
+
+        let calls = AtomicU32::new(0);
+        let result = resilience
+            .read(CLOUD_STORAGE, || flaky(&calls, ErrorKind::ConnectionRefused, 100))
+            .await;
+        assert!(matches!(result, Err(ResilienceError::Failed(_))));
+        // Stopped retrying once the service was offline
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(resilience.monitor().is_offline(CLOUD_STORAGE));
+
+        let result = resilience
+            .read(CLOUD_STORAGE, || flaky(&calls, ErrorKind::ConnectionRefused, 0))
+            .await;
+        assert!(matches!(result, Err(ResilienceError::Offline { .. })));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // Other services are unaffected
+        let result = resilience.read(COLLABORATION, || async { Ok::<_, Error>(1) }).await;
+        assert_eq!(result.unwrap(), 1);
+    }
+}
@@ -0,0 +1,383 @@
+//! Connection status and offline detection
+//!
+//! The [`ConnectivityMonitor`] hears about every call the resiliency layer
+//! makes and keeps a [`ServiceStatus`] per remote service. A service that
+//! fails `offline_after` times in a row is taken offline: calls to it fail
+//! fast instead of piling up timeouts, and one probe call at a time is let
+//! through on a backoff schedule until it answers again. The operating
+//! system's own "network down" signal takes everything offline at once.
+//!
+//! The combined [`ConnectionStatus`] is published on a watch channel for
+//! the status bar, with a one-line [`ConnectionStatus::message`] for users.
+
+use super::backoff::BackoffPolicy;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// Weight of the newest sample in the latency average
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// Health of a remote service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ServiceState {
+    /// Answering normally
+    Online,
+    /// Recent calls failed and are being retried
+    Degraded,
+    /// Unreachable; only probes are sent
+    Offline,
+}
+
+/// Status of one remote service
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceStatus {
+    /// Service name
+    pub service: String,
+    /// Health
+    pub state: ServiceState,
+    /// Failures since the last success
+    pub consecutive_failures: u32,
+    /// Most recent failure
+    pub last_error: Option<String>,
+    /// When the service went offline
+    pub offline_since: Option<DateTime<Utc>>,
+    /// When the next probe may go out, while offline
+    pub next_probe: Option<DateTime<Utc>>,
+    /// Smoothed round-trip time of successful calls, in milliseconds
+    pub latency_ms: Option<f64>,
+}
+
+impl ServiceStatus {
+    fn new(service: &str) -> Self {
+        Self {
+            service: service.to_string(),
+            state: ServiceState::Online,
+            consecutive_failures: 0,
+            last_error: None,
+            offline_since: None,
+            next_probe: None,
+            latency_ms: None,
+        }
+    }
+}
+
+/// Status of every service the client talks to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionStatus {
+    /// Whether the operating system reports a network connection
+    pub network_available: bool,
+    /// Services by name
+    pub services: BTreeMap<String, ServiceStatus>,
+}
+
+impl Default for ConnectionStatus {
+    fn default() -> Self {
+        Self {
+            network_available: true,
+            services: BTreeMap::new(),
+        }
+    }
+}
+
+impl ConnectionStatus {
+    /// Worst state across all services
+    pub fn state(&self) -> ServiceState {
+        if !self.network_available {
+            return ServiceState::Offline;
+        }
+        self.services
+            .values()
+            .map(|s| s.state)
+            .max()
+            .unwrap_or(ServiceState::Online)
+    }
+
+    /// Whether anything is offline
+    pub fn is_offline(&self) -> bool {
+        self.state() == ServiceState::Offline
+    }
+
+    /// Status line for the user
+    pub fn message(&self) -> String {
+        if !self.network_available {
+            return "Offline - no network connection; changes are kept locally".to_string();
+        }
+        let names = |state: ServiceState| {
+            self.services
+                .values()
+                .filter(|s| s.state == state)
+                .map(|s| s.service.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self.state() {
+            ServiceState::Online => "Connected".to_string(),
+            ServiceState::Degraded => format!("Connection unstable - retrying {}", names(ServiceState::Degraded)),
+            ServiceState::Offline => {
+                let next = self
+                    .services
+                    .values()
+                    .filter_map(|s| s.next_probe)
+                    .min()
+                    .map(|at| (at - Utc::now()).num_seconds().max(0));
+                match next {
+                    Some(secs) => format!(
+                        "Offline - {} unreachable; reconnecting in {} s",
+                        names(ServiceState::Offline),
+                        secs
+                    ),
+                    None => format!("Offline - {} unreachable", names(ServiceState::Offline)),
+                }
+            }
+        }
+    }
+}
+
+/// Whether a call may go out now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    /// The service is reachable
+    Allowed,
+    /// The service is offline and this call is the probe
+    Probe,
+    /// The service is offline; try again after the delay
+    Offline {
+        /// Time until the next probe
+        retry_in: Duration,
+    },
+}
+
+/// Offline bookkeeping for one service
+#[derive(Debug, Default)]
+struct ProbeState {
+    /// Probes sent since going offline
+    probes: u32,
+    /// Earliest time for the next probe
+    next: Option<Instant>,
+}
+
+/// Tracks reachability of remote services
+#[derive(Debug)]
+pub struct ConnectivityMonitor {
+    offline_after: u32,
+    probe: BackoffPolicy,
+    probes: Mutex<HashMap<String, ProbeState>>,
+    status: watch::Sender<ConnectionStatus>,
+}
+
+impl ConnectivityMonitor {
+    /// Monitor that takes a service offline after `offline_after`
+    /// consecutive failures and probes it on the `probe` schedule
+    pub fn new(offline_after: u32, probe: BackoffPolicy) -> Self {
+        let (status, _) = watch::channel(ConnectionStatus::default());
+        Self {
+            offline_after: offline_after.max(1),
+            probe,
+            probes: Mutex::new(HashMap::new()),
+            status,
+        }
+    }
+
+    /// Current status of all services
+    pub fn status(&self) -> ConnectionStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Current status of one service
+    pub fn service(&self, service: &str) -> Option<ServiceStatus> {
+        self.status.borrow().services.get(service).cloned()
+    }
+
+    /// Whether a service is offline, or the network is down
+    pub fn is_offline(&self, service: &str) -> bool {
+        let status = self.status.borrow();
+        !status.network_available
+            || status
+                .services
+                .get(service)
+                .is_some_and(|s| s.state == ServiceState::Offline)
+    }
+
+    /// Receive every status change
+    pub fn subscribe(&self) -> watch::Receiver<ConnectionStatus> {
+        self.status.subscribe()
+    }
+
+    /// Decide whether a call to `service` may go out now
+    pub fn admit(&self, service: &str) -> Admission {
+        if !self.status.borrow().network_available {
+            return Admission::Offline {
+                retry_in: self.probe.initial_delay,
+            };
+        }
+        if !self.is_offline(service) {
+            return Admission::Allowed;
+        }
+
+        let mut probes = self.probes.lock();
+        let probe = probes.entry(service.to_string()).or_default();
+        let now = Instant::now();
+        match probe.next {
+            Some(next) if next > now => Admission::Offline { retry_in: next - now },
+            _ => {
+                // Hold back further calls until this probe has had time to
+                // answer
+                let wait = self.probe.delay(probe.probes);
+                probe.probes += 1;
+                probe.next = Some(now + wait);
+                self.update(service, |s| s.next_probe = Some(Utc::now() + chrono_duration(wait)));
+                Admission::Probe
+            }
+        }
+    }
+
+    /// Record a call that reached the service
+    pub fn record_success(&self, service: &str, latency: Duration) {
+        self.probes.lock().remove(service);
+        self.update(service, |s| {
+            let ms = latency.as_secs_f64() * 1000.0;
+            s.latency_ms = Some(match s.latency_ms {
+                Some(avg) => avg + LATENCY_SMOOTHING * (ms - avg),
+                None => ms,
+            });
+            s.state = ServiceState::Online;
+            s.consecutive_failures = 0;
+            s.offline_since = None;
+            s.next_probe = None;
+        });
+    }
+
+    /// Record a call that failed to reach the service
+    pub fn record_failure(&self, service: &str, error: &str) {
+        let offline_after = self.offline_after;
+        let went_offline = self.update(service, |s| {
+            s.consecutive_failures += 1;
+            s.last_error = Some(error.to_string());
+            if s.consecutive_failures >= offline_after {
+                let was_offline = s.state == ServiceState::Offline;
+                s.state = ServiceState::Offline;
+                s.offline_since.get_or_insert_with(Utc::now);
+                !was_offline
+            } else {
+                s.state = ServiceState::Degraded;
+                false
+            }
+        });
+
+        if went_offline {
+            log::warn!("{} is offline after {} failures: {}", service, offline_after, error);
+            let mut probes = self.probes.lock();
+            let probe = probes.entry(service.to_string()).or_default();
+            let wait = self.probe.delay(0);
+            probe.probes = 1;
+            probe.next = Some(Instant::now() + wait);
+            drop(probes);
+            self.update(service, |s| s.next_probe = Some(Utc::now() + chrono_duration(wait)));
+        }
+    }
+
+    /// Report the operating system's view of network availability
+    pub fn set_network_available(&self, available: bool) {
+        self.status.send_if_modified(|status| {
+            let changed = status.network_available != available;
+            status.network_available = available;
+            changed
+        });
+        if available {
+            // Probe everything straight away instead of waiting out backoff
+            for probe in self.probes.lock().values_mut() {
+                probe.next = None;
+            }
+        }
+    }
+
+    fn update<R>(&self, service: &str, f: impl FnOnce(&mut ServiceStatus) -> R) -> R {
+        let mut result = None;
+        self.status.send_modify(|status| {
+            let entry = status
+                .services
+                .entry(service.to_string())
+                .or_insert_with(|| ServiceStatus::new(service));
+            result = Some(f(entry));
+        });
+        result.expect("status update ran")
+    }
+}
+
+impl Default for ConnectivityMonitor {
+    fn default() -> Self {
+        Self::new(
+            3,
+            BackoffPolicy::exponential(Duration::from_secs(1), Duration::from_secs(60), 2.0).with_jitter(0.2),
+        )
+    }
+}
+
+fn chrono_duration(duration: Duration) -> chrono::Duration {
+    chrono::Duration::from_std(duration).unwrap_or_else(|_| chrono::Duration::days(365))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(probe_delay: Duration) -> ConnectivityMonitor {
+        ConnectivityMonitor::new(2, BackoffPolicy::exponential(probe_delay, probe_delay, 1.0))
+    }
+
+    #[test]
+    fn test_failures_take_service_offline_until_success() {
+        let monitor = monitor(Duration::from_secs(60));
+        let mut rx = monitor.subscribe();
+
+        monitor.record_failure("cloud-storage", "connection refused");
+        assert_eq!(monitor.service("cloud-storage").unwrap().state, ServiceState::Degraded);
+        assert_eq!(monitor.admit("cloud-storage"), Admission::Allowed);
+        assert!(monitor.status().message().contains("retrying cloud-storage"));
+
+        monitor.record_failure("cloud-storage", "connection refused");
+        assert!(monitor.is_offline("cloud-storage"));
+        assert!(!monitor.is_offline("collaboration"));
+        assert!(matches!(monitor.admit("cloud-storage"), Admission::Offline { .. }));
+        assert!(rx.has_changed().unwrap());
+        assert!(rx.borrow_and_update().is_offline());
+        assert!(monitor
+            .status()
+            .message()
+            .starts_with("Offline - cloud-storage unreachable"));
+
+        monitor.record_success("cloud-storage", Duration::from_millis(40));
+        let status = monitor.service("cloud-storage").unwrap();
+        assert_eq!(status.state, ServiceState::Online);
+        assert_eq!(status.latency_ms, Some(40.0));
+        assert_eq!(monitor.status().message(), "Connected");
+    }
+
+    #[test]
+    fn test_offline_service_admits_one_probe_at_a_time() {
+        let monitor = monitor(Duration::ZERO);
+        monitor.record_failure("api", "timeout");
+        monitor.record_failure("api", "timeout");
+
+        // Zero probe delay: the probe is due at once
+        assert_eq!(monitor.admit("api"), Admission::Probe);
+
+        let slow = ConnectivityMonitor::new(
+            1,
+            BackoffPolicy::exponential(Duration::from_secs(30), Duration::from_secs(30), 1.0),
+        );
+        slow.record_failure("api", "timeout");
+        assert!(matches!(slow.admit("api"), Admission::Offline { retry_in } if retry_in > Duration::from_secs(29)));
+
+        // Network coming back skips the wait
+        slow.set_network_available(false);
+        assert!(slow.status().message().contains("no network"));
+        slow.set_network_available(true);
+        assert_eq!(slow.admit("api"), Admission::Probe);
+        assert!(matches!(slow.admit("api"), Admission::Offline { .. }));
+    }
+}
@@ -1,6 +1,7 @@
 //! Tenant Configuration Management
 //!
 //! Per-tenant feature flags, custom branding, configuration inheritance, and override cascading.
+//! Flags under a progressive rollout are resolved through [`RolloutManager`].
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -11,6 +12,7 @@ use serde_json::Value as JsonValue;
 use thiserror::Error;
 
 use super::context::TenantId;
+use super::rollout::RolloutManager;
 
 /// Configuration errors
 #[derive(Error, Debug)]
@@ -301,6 +303,8 @@ pub struct ConfigManager {
     configs: DashMap<TenantId, Arc<RwLock<TenantConfig>>>,
     /// Default configuration template
     default_config: Arc<RwLock<TenantConfig>>,
+    /// Progressive flag rollouts
+    rollouts: Arc<RolloutManager>,
 }
 
 impl ConfigManager {
@@ -310,9 +314,23 @@ impl ConfigManager {
         Self {
             configs: DashMap::new(),
             default_config: Arc::new(RwLock::new(TenantConfig::new(default_tenant_id))),
+            rollouts: Arc::new(RolloutManager::new()),
         }
     }
 
+    /// Create a configuration manager sharing a rollout registry
+    pub fn with_rollouts(rollouts: Arc<RolloutManager>) -> Self {
+        Self {
+            rollouts,
+            ..Self::new()
+        }
+    }
+
+    /// Get the flag rollout registry
+    pub fn rollouts(&self) -> &Arc<RolloutManager> {
+        &self.rollouts
+    }
+
     /// Set the default configuration template
     pub fn set_default(&self, config: TenantConfig) {
         *self.default_config.write() = config;
@@ -409,9 +427,12 @@ impl ConfigManager {
     }
 
     /// Check if a feature is enabled for a tenant
+    ///
+    /// The tenant's configured value applies unless the flag is under a
+    /// rollout that puts the tenant on green or engages its kill switch.
     pub fn is_feature_enabled(&self, tenant_id: &TenantId, feature: &str) -> bool {
         if let Ok(config) = self.get_effective_config(tenant_id) {
-            match feature {
+            let configured = match feature {
                 "collaboration" => config.features.collaboration,
                 "cloud_sync" => config.features.cloud_sync,
                 "advanced_rendering" => config.features.advanced_rendering,
@@ -420,7 +441,10 @@ impl ConfigManager {
                 "workflows" => config.features.workflows,
                 "analytics" => config.features.analytics,
                 _ => config.features.is_enabled(feature),
-            }
+            };
+            self.rollouts
+                .evaluate(feature, tenant_id, &config, configured)
+                .enabled
         } else {
             false
         }
@@ -587,6 +611,24 @@ mod tests {
         assert!(manager.is_feature_enabled(&tenant_id, "ai_features"));
     }
 
+    #[test]
+    fn test_feature_check_with_rollout() {
+        use super::super::rollout::FlagRollout;
+
+        let manager = ConfigManager::new();
+        let tenant_id = TenantId::new_org(Uuid::new_v4());
+        manager.create_config(tenant_id.clone(), Tier::Basic);
+        assert!(!manager.is_feature_enabled(&tenant_id, "ai_features"));
+
+        let mut flag = FlagRollout::new("ai_features", "AI assistant");
+        flag.percentage = 100;
+        manager.rollouts().create_flag(flag, "ops").unwrap();
+        assert!(manager.is_feature_enabled(&tenant_id, "ai_features"));
+
+        manager.rollouts().engage_kill_switch("ai_features", "ops", None).unwrap();
+        assert!(!manager.is_feature_enabled(&tenant_id, "ai_features"));
+    }
+
     #[test]
     fn test_config_export_import() {
         let manager = ConfigManager::new();
@@ -5,7 +5,9 @@
 //! - **Tenant Context Management**: Thread-local context, async propagation, hierarchical tenants
 //! - **Resource Isolation**: Memory quotas, CPU limits, storage quotas, network bandwidth limits
 //! - **Data Partitioning**: Schema-based isolation, row-level security, encryption key separation
+//! - **Row-Level Security**: PostgreSQL policy generation, table migration, per-transaction tenant binding
 //! - **Configuration Management**: Per-tenant feature flags, branding, configuration inheritance
+//! - **Flag Rollouts**: Blue/green percentage rollouts, cohort targeting, kill switches, change audit
 //! - **Billing & Metering**: Resource usage tracking, API call counting, billing event generation
 //! - **Lifecycle Management**: Provisioning, suspension, data export, GDPR-compliant deletion
 //!
@@ -81,7 +83,9 @@
 pub mod context;
 pub mod isolation;
 pub mod partition;
+pub mod rls;
 pub mod config;
+pub mod rollout;
 pub mod metering;
 pub mod lifecycle;
 
@@ -103,11 +107,21 @@ pub use partition::{
     PartitionError, PartitionResult,
 };
 
+pub use rls::{
+    TenantTable, RlsPlan, bind_tenant, begin_for_tenant, begin_for_current_tenant,
+    TENANT_SETTING, USER_SETTING,
+};
+
 pub use config::{
     ConfigManager, TenantConfig, FeatureFlags, BrandingConfig,
     UiPreferences, Tier, ConfigError, ConfigResult,
 };
 
+pub use rollout::{
+    RolloutManager, FlagRollout, FlagUpdate, FlagDecision, DecisionReason,
+    Cohort, FlagChange, FlagChangeKind, RolloutError, RolloutResult,
+};
+
 pub use metering::{
     MeteringManager, MetricType, UsageRecord, BillingPeriodUsage,
     PricingModel, MetricPricing, BillingEvent, BillingEventType,
@@ -144,6 +158,10 @@ pub enum TenantError {
     #[error("Configuration error: {0}")]
     Config(#[from] ConfigError),
 
+    /// Flag rollout error
+    #[error("Rollout error: {0}")]
+    Rollout(#[from] RolloutError),
+
     /// Metering error
     #[error("Metering error: {0}")]
     Metering(#[from] MeteringError),
@@ -1,7 +1,8 @@
 //! Data Partitioning and Isolation
 //!
 //! Implements schema-based isolation, row-level security, encryption key separation,
-//! and cross-tenant query prevention.
+//! and cross-tenant query prevention. PostgreSQL policies for shared-schema
+//! tables are generated in [`rls`](super::rls).
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -32,6 +33,9 @@ pub enum PartitionError {
 
     #[error("Row-level security policy violation: {0}")]
     RlsPolicyViolation(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
 }
 
 pub type PartitionResult<T> = Result<T, PartitionError>;
@@ -130,6 +134,11 @@ impl DataPartitionManager {
         }
     }
 
+    /// Partitioning configuration
+    pub fn config(&self) -> &SchemaConfig {
+        &self.config
+    }
+
     /// Initialize partition for a new tenant
     pub fn initialize_tenant(&self, tenant_id: TenantId) -> PartitionResult<TenantPartitionInfo> {
         let schema_name = self.config.schema_name(&tenant_id);
@@ -0,0 +1,580 @@
+//! PostgreSQL Row-Level Security
+//!
+//! With shared-schema partitioning every tenant's rows live in the same
+//! tables, told apart by a tenant column, and a query that forgets its
+//! `WHERE tenant_id = ...` reads everyone's data. Row-level security moves
+//! that filter into the database: [`DataPartitionManager::rls_plan`] turns
+//! the partition configuration and a list of tenant tables into a migration
+//! that adds and backfills the tenant column on existing tables, enables and
+//! forces RLS on them, and creates policies that only show a session the
+//! rows of the tenant bound to it, or of that tenant's workspaces and
+//! projects. [`begin_for_tenant`] opens a transaction bound to the tenant of
+//! a [`TenantContext`].
+//!
+//! A session with no tenant bound sees no rows and cannot write any: the
+//! policies compare against `current_setting(..., true)`, which is NULL
+//! until [`bind_tenant`] sets it. Superusers and roles with `BYPASSRLS`
+//! still see everything, so the application must not connect as one.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use caddy::enterprise::tenant::{DataPartitionManager, PartitionStrategy, SchemaConfig, TenantContext, TenantId};
+//! use caddy::enterprise::tenant::rls::{begin_for_tenant, TenantTable};
+//! use sqlx::PgPool;
+//! use uuid::Uuid;
+//!
+//! # async fn example(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+//! let manager = DataPartitionManager::new(SchemaConfig {
+//!     strategy: PartitionStrategy::SharedSchema,
+//!     ..Default::default()
+//! });
+//! let plan = manager.rls_plan(&[
+//!     TenantTable::new("documents").backfill("'org:' || owner_org_id::text"),
+//!     TenantTable::new("layers"),
+//! ])?;
+//! plan.apply(&pool).await?;
+//!
+//! let context = TenantContext::new(TenantId::new_org(Uuid::new_v4()));
+//! let mut tx = begin_for_tenant(&pool, &context).await?;
+//! // Only this tenant's documents are visible
+//! let count: i64 = sqlx::query_scalar("SELECT count(*) FROM documents").fetch_one(&mut *tx).await?;
+//! tx.commit().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool, Postgres, Transaction};
+
+use super::context::{get_context, TenantContext};
+use super::partition::{
+    DataPartitionManager, PartitionError, PartitionResult, PartitionStrategy, RlsPolicy,
+};
+
+/// Session setting holding the bound tenant, as [`TenantId`]'s display form
+///
+/// [`TenantId`]: super::context::TenantId
+pub const TENANT_SETTING: &str = "caddy.tenant_id";
+
+/// Session setting holding the bound user, empty when the context has none
+pub const USER_SETTING: &str = "caddy.user_id";
+
+/// Default name of the tenant column
+pub const DEFAULT_TENANT_COLUMN: &str = "tenant_id";
+
+/// A table whose rows belong to tenants
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantTable {
+    /// Table name, optionally schema-qualified
+    pub table: String,
+    /// Column holding the owning tenant
+    pub tenant_column: String,
+    /// SQL expression giving the tenant of rows that predate the column
+    pub backfill: Option<String>,
+}
+
+impl TenantTable {
+    /// A table with the default tenant column and no backfill
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            tenant_column: DEFAULT_TENANT_COLUMN.to_string(),
+            backfill: None,
+        }
+    }
+
+    /// Use a different tenant column
+    pub fn tenant_column(mut self, column: impl Into<String>) -> Self {
+        self.tenant_column = column.into();
+        self
+    }
+
+    /// Fill the tenant column of existing rows from `expression`, evaluated
+    /// per row, e.g. `'org:' || owner_org_id::text`
+    ///
+    /// The expression is trusted SQL from configuration and is not escaped.
+    /// Without one, migrating a table that already has rows without a
+    /// tenant fails and rolls back.
+    pub fn backfill(mut self, expression: impl Into<String>) -> Self {
+        self.backfill = Some(expression.into());
+        self
+    }
+}
+
+/// The statements enabling row-level security on a set of tables, and
+/// those undoing it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RlsPlan {
+    /// One tenant isolation policy per table
+    pub policies: Vec<RlsPolicy>,
+    /// Statements enabling RLS, in order
+    pub up: Vec<String>,
+    /// Statements disabling RLS again, in order
+    ///
+    /// Tenant columns and their data are kept.
+    pub down: Vec<String>,
+}
+
+impl RlsPlan {
+    /// Run [`up`](Self::up) in one transaction
+    pub async fn apply(&self, pool: &PgPool) -> PartitionResult<()> {
+        run_all(pool, &self.up).await
+    }
+
+    /// Run [`down`](Self::down) in one transaction
+    pub async fn revert(&self, pool: &PgPool) -> PartitionResult<()> {
+        run_all(pool, &self.down).await
+    }
+
+    /// The up statements as one script
+    pub fn up_sql(&self) -> String {
+        script(&self.up)
+    }
+
+    /// The down statements as one script
+    pub fn down_sql(&self) -> String {
+        script(&self.down)
+    }
+}
+
+impl RlsPolicy {
+    /// `CREATE POLICY` statements, one per operation
+    ///
+    /// PostgreSQL policies cover a single command each, so a policy for
+    /// several operations becomes several policies, suffixed with the
+    /// operation's name.
+    pub fn create_sql(&self) -> Vec<String> {
+        self.per_operation()
+            .into_iter()
+            .map(|(name, operation)| {
+                let using = format!(" USING ({})", self.condition);
+                let check = format!(" WITH CHECK ({})", self.condition);
+                let clauses = match operation.as_str() {
+                    "SELECT" | "DELETE" => using,
+                    "INSERT" => check,
+                    _ => using + &check,
+                };
+                format!(
+                    "CREATE POLICY {} ON {} AS PERMISSIVE FOR {}{}",
+                    quote_ident(&name),
+                    self.table,
+                    operation,
+                    clauses
+                )
+            })
+            .collect()
+    }
+
+    /// `DROP POLICY` statements matching [`create_sql`](Self::create_sql)
+    pub fn drop_sql(&self) -> Vec<String> {
+        self.per_operation()
+            .into_iter()
+            .map(|(name, _)| {
+                format!(
+                    "DROP POLICY IF EXISTS {} ON {}",
+                    quote_ident(&name),
+                    self.table
+                )
+            })
+            .collect()
+    }
+
+    fn per_operation(&self) -> Vec<(String, String)> {
+        let operations: Vec<String> = if self.operations.is_empty() {
+            vec!["ALL".to_string()]
+        } else {
+            self.operations.iter().map(|op| op.to_uppercase()).collect()
+        };
+        if operations.len() == 1 {
+            return vec![(self.name.clone(), operations[0].clone())];
+        }
+        operations
+            .into_iter()
+            .map(|op| (format!("{}_{}", self.name, op.to_lowercase()), op))
+            .collect()
+    }
+}
+
+impl DataPartitionManager {
+    /// Row-level security for `tables` under this manager's strategy
+    ///
+    /// Only shared-schema partitioning (and the shared part of hybrid
+    /// partitioning) keeps tenants in the same tables; separate schemas and
+    /// databases are already isolated, and asking for RLS there is a
+    /// configuration error. Unqualified table names are taken to be in the
+    /// shared schema.
+    pub fn rls_plan(&self, tables: &[TenantTable]) -> PartitionResult<RlsPlan> {
+        let shared_schema = match self.config().strategy {
+            PartitionStrategy::SharedSchema | PartitionStrategy::Hybrid { .. } => "public",
+            ref strategy => {
+                return Err(PartitionError::InvalidConfig(format!(
+                    "Row-level security needs tenants sharing tables, not {:?}",
+                    strategy
+                )))
+            }
+        };
+
+        let mut plan = RlsPlan {
+            policies: Vec::new(),
+            up: Vec::new(),
+            down: Vec::new(),
+        };
+        for table in tables {
+            let (schema, name) = match table.table.split_once('.') {
+                Some((schema, name)) => (schema, name),
+                None => (shared_schema, table.table.as_str()),
+            };
+            for ident in [schema, name, table.tenant_column.as_str()] {
+                check_ident(ident)?;
+            }
+            let qualified = format!("{}.{}", quote_ident(schema), quote_ident(name));
+            let column = quote_ident(&table.tenant_column);
+            let policy = RlsPolicy {
+                name: format!("{}_tenant_isolation", name),
+                table: qualified.clone(),
+                condition: tenant_condition(&column),
+                operations: vec!["ALL".to_string()],
+            };
+
+            // Migrate the table: tenant column, backfilled, required, and
+            // defaulting to the bound tenant so inserts need not name it
+            plan.up.push(format!(
+                "ALTER TABLE {} ADD COLUMN IF NOT EXISTS {} TEXT",
+                qualified, column
+            ));
+            if let Some(expression) = &table.backfill {
+                plan.up.push(format!(
+                    "UPDATE {} SET {} = {} WHERE {} IS NULL",
+                    qualified, column, expression, column
+                ));
+            }
+            plan.up.push(format!(
+                "ALTER TABLE {} ALTER COLUMN {} SET NOT NULL",
+                qualified, column
+            ));
+            plan.up.push(format!(
+                "ALTER TABLE {} ALTER COLUMN {} SET DEFAULT current_setting('{}', true)",
+                qualified, column, TENANT_SETTING
+            ));
+            plan.up.push(format!(
+                "CREATE INDEX IF NOT EXISTS {} ON {} ({})",
+                quote_ident(&format!("{}_{}_idx", name, table.tenant_column)),
+                qualified,
+                column
+            ));
+            // FORCE applies the policies to the table's owner too
+            plan.up.push(format!(
+                "ALTER TABLE {} ENABLE ROW LEVEL SECURITY",
+                qualified
+            ));
+            plan.up.push(format!(
+                "ALTER TABLE {} FORCE ROW LEVEL SECURITY",
+                qualified
+            ));
+            plan.up.extend(policy.drop_sql());
+            plan.up.extend(policy.create_sql());
+
+            plan.down.extend(policy.drop_sql());
+            plan.down.push(format!(
+                "ALTER TABLE {} NO FORCE ROW LEVEL SECURITY",
+                qualified
+            ));
+            plan.down.push(format!(
+                "ALTER TABLE {} DISABLE ROW LEVEL SECURITY",
+                qualified
+            ));
+            plan.down.push(format!(
+                "ALTER TABLE {} ALTER COLUMN {} DROP DEFAULT",
+                qualified, column
+            ));
+
+            plan.policies.push(policy);
+        }
+        Ok(plan)
+    }
+}
+
+/// Rows of the bound tenant and of its workspaces and projects, whose
+/// identifiers extend the tenant's with `/`
+fn tenant_condition(column: &str) -> String {
+    let bound = format!("current_setting('{}', true)", TENANT_SETTING);
+    format!("{column}::text = {bound} OR starts_with({column}::text, {bound} || '/')")
+}
+
+/// Bind `context`'s tenant and user to the current transaction
+///
+/// The settings are transaction-local, so a pooled connection never carries
+/// one request's tenant into the next; outside a transaction they last for
+/// a single statement only.
+pub async fn bind_tenant(conn: &mut PgConnection, context: &TenantContext) -> PartitionResult<()> {
+    sqlx::query("SELECT set_config($1, $2, true), set_config($3, $4, true)")
+        .bind(TENANT_SETTING)
+        .bind(context.tenant_id.to_string())
+        .bind(USER_SETTING)
+        .bind(context.user_id.map(|id| id.to_string()).unwrap_or_default())
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Begin a transaction bound to `context`'s tenant
+pub async fn begin_for_tenant(
+    pool: &PgPool,
+    context: &TenantContext,
+) -> PartitionResult<Transaction<'static, Postgres>> {
+    let mut tx = pool.begin().await?;
+    bind_tenant(&mut tx, context).await?;
+    Ok(tx)
+}
+
+/// Begin a transaction bound to the current thread's tenant context
+pub async fn begin_for_current_tenant(
+    pool: &PgPool,
+) -> PartitionResult<Transaction<'static, Postgres>> {
+    let context = get_context().map_err(|e| PartitionError::InvalidConfig(e.to_string()))?;
+    begin_for_tenant(pool, &context).await
+}
+
+async fn run_all(pool: &PgPool, statements: &[String]) -> PartitionResult<()> {
+    let mut tx = pool.begin().await?;
+    for statement in statements {
+        sqlx::query(statement).execute(&mut *tx).await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+fn script(statements: &[String]) -> String {
+    statements.iter().map(|s| format!("{};\n", s)).collect()
+}
+
+/// Plain SQL identifiers only: letters, digits and underscores
+fn check_ident(ident: &str) -> PartitionResult<()> {
+    let mut chars = ident.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(PartitionError::InvalidConfig(format!(
+            "Invalid SQL identifier: {:?}",
+            ident
+        )))
+    }
+}
+
+fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enterprise::tenant::context::TenantId;
+    use crate::enterprise::tenant::partition::SchemaConfig;
+    use uuid::Uuid;
+
+    fn shared_manager() -> DataPartitionManager {
+        DataPartitionManager::new(SchemaConfig {
+            strategy: PartitionStrategy::SharedSchema,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_plan_migrates_and_isolates_tables() {
+        let plan = shared_manager()
+            .rls_plan(&[
+                TenantTable::new("documents").backfill("'org:' || owner_org::text"),
+                TenantTable::new("audit.events").tenant_column("owner"),
+            ])
+            .unwrap();
+
+        assert_eq!(plan.policies.len(), 2);
+        assert_eq!(plan.policies[0].table, "\"public\".\"documents\"");
+        assert_eq!(plan.policies[1].table, "\"audit\".\"events\"");
+
+        let up = plan.up_sql();
+        assert!(up.contains(
+            "ALTER TABLE \"public\".\"documents\" ADD COLUMN IF NOT EXISTS \"tenant_id\" TEXT;"
+        ));
+        assert!(up.contains(
+            "UPDATE \"public\".\"documents\" SET \"tenant_id\" = 'org:' || owner_org::text"
+        ));
+        assert!(up.contains("ALTER TABLE \"audit\".\"events\" FORCE ROW LEVEL SECURITY;"));
+        assert!(up.contains(
+            "CREATE POLICY \"events_tenant_isolation\" ON \"audit\".\"events\" AS PERMISSIVE FOR ALL \
+             USING (\"owner\"::text = current_setting('caddy.tenant_id', true) \
+             OR starts_with(\"owner\"::text, current_setting('caddy.tenant_id', true) || '/'))"
+        ));
+        // The backfill runs before the column becomes required
+        let position = |needle: &str| up.find(needle).unwrap();
+        assert!(
+            position("UPDATE \"public\".\"documents\"") < position("\"tenant_id\" SET NOT NULL")
+        );
+
+        let down = plan.down_sql();
+        assert!(down.contains(
+            "DROP POLICY IF EXISTS \"documents_tenant_isolation\" ON \"public\".\"documents\";"
+        ));
+        assert!(down.contains("DISABLE ROW LEVEL SECURITY"));
+        assert!(!down.contains("DROP COLUMN"));
+    }
+
+    #[test]
+    fn test_plan_rejects_unsafe_config() {
+        let separate = DataPartitionManager::new(SchemaConfig::default());
+        assert!(matches!(
+            separate.rls_plan(&[TenantTable::new("documents")]),
+            Err(PartitionError::InvalidConfig(_))
+        ));
+
+        let manager = shared_manager();
+        for table in [
+            TenantTable::new("documents; DROP TABLE users"),
+            TenantTable::new("documents").tenant_column("tenant\"id"),
+            TenantTable::new("a.b.c"),
+        ] {
+            assert!(manager.rls_plan(&[table]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_policy_per_operation() {
+        let policy = RlsPolicy {
+            name: "own_rows".to_string(),
+            table: "\"public\".\"notes\"".to_string(),
+            condition: "owner = current_user".to_string(),
+            operations: vec!["select".to_string(), "INSERT".to_string()],
+        };
+        assert_eq!(
+            policy.create_sql(),
+            vec![
+                "CREATE POLICY \"own_rows_select\" ON \"public\".\"notes\" AS PERMISSIVE FOR SELECT USING (owner = current_user)",
+                "CREATE POLICY \"own_rows_insert\" ON \"public\".\"notes\" AS PERMISSIVE FOR INSERT WITH CHECK (owner = current_user)",
+            ]
+        );
+        assert_eq!(policy.drop_sql().len(), 2);
+    }
+
+    /// Runs against a scratch PostgreSQL database named by
+    /// `CADDY_TEST_POSTGRES_URL`, connecting as a role allowed to create
+    /// roles; skipped when it is unset
+    #[tokio::test]
+    async fn test_cross_tenant_reads_are_impossible() {
+        let Ok(url) = std::env::var("CADDY_TEST_POSTGRES_URL") else {
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        let table = format!("rls_probe_{}", Uuid::new_v4().simple());
+        let (alice, bob) = (
+            TenantId::new_org(Uuid::new_v4()),
+            TenantId::new_org(Uuid::new_v4()),
+        );
+        let alice_ws = TenantId::new_workspace(alice.org_id, Uuid::new_v4());
+
+        // An existing table whose rows name their organization another way
+        sqlx::query(&format!(
+            "CREATE TABLE {} (title TEXT NOT NULL, owner_org TEXT NOT NULL)",
+            table
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+        for (title, owner) in [("alice plan", &alice), ("bob plan", &bob)] {
+            sqlx::query(&format!("INSERT INTO {} VALUES ($1, $2)", table))
+                .bind(title)
+                .bind(owner.to_string())
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        let plan = shared_manager()
+            .rls_plan(&[TenantTable::new(table.clone()).backfill("owner_org")])
+            .unwrap();
+        plan.apply(&pool).await.unwrap();
+
+        // The application's role: neither superuser nor table owner
+        sqlx::query(
+            "DO $$ BEGIN CREATE ROLE caddy_rls_probe NOLOGIN; EXCEPTION WHEN duplicate_object THEN NULL; END $$",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(&format!(
+            "GRANT SELECT, INSERT, UPDATE, DELETE ON {} TO caddy_rls_probe",
+            table
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let titles = |context: Option<TenantContext>, sql: String| {
+            let pool = pool.clone();
+            async move {
+                let mut tx = pool.begin().await.unwrap();
+                sqlx::query("SET LOCAL ROLE caddy_rls_probe")
+                    .execute(&mut *tx)
+                    .await
+                    .unwrap();
+                if let Some(context) = context {
+                    bind_tenant(&mut tx, &context).await.unwrap();
+                }
+                let rows: Vec<String> = sqlx::query_scalar(&sql).fetch_all(&mut *tx).await.unwrap();
+                tx.rollback().await.unwrap();
+                rows
+            }
+        };
+        let select_all = format!("SELECT title FROM {} ORDER BY title", table);
+        let context = |tenant: &TenantId| Some(TenantContext::new(tenant.clone()));
+
+        assert_eq!(
+            titles(context(&alice), select_all.clone()).await,
+            vec!["alice plan"]
+        );
+        assert_eq!(
+            titles(context(&bob), select_all.clone()).await,
+            vec!["bob plan"]
+        );
+        // Asking for the other tenant's rows by name finds nothing
+        let by_owner = format!("SELECT title FROM {} WHERE tenant_id = '{}'", table, bob);
+        assert!(titles(context(&alice), by_owner).await.is_empty());
+        // A workspace does not see its organization's rows; no tenant sees nothing
+        assert!(titles(context(&alice_ws), select_all.clone())
+            .await
+            .is_empty());
+        assert!(titles(None, select_all.clone()).await.is_empty());
+
+        // Writes: the bound tenant is the default, another tenant is refused
+        let mut tx = pool.begin().await.unwrap();
+        sqlx::query("SET LOCAL ROLE caddy_rls_probe")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        bind_tenant(&mut tx, &TenantContext::new(alice_ws.clone()))
+            .await
+            .unwrap();
+        sqlx::query(&format!(
+            "INSERT INTO {} (title, owner_org) VALUES ('ws note', '')",
+            table
+        ))
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        let forged = sqlx::query(&format!("INSERT INTO {} VALUES ('forged', '', $1)", table))
+            .bind(bob.to_string())
+            .execute(&mut *tx)
+            .await;
+        assert!(forged.is_err());
+        tx.rollback().await.unwrap();
+
+        plan.revert(&pool).await.unwrap();
+        sqlx::query(&format!("DROP TABLE {}", table))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
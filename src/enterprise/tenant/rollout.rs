@@ -0,0 +1,539 @@
+//! Progressive Feature Flag Rollout
+//!
+//! Blue/green rollout of per-tenant feature flags. A flag under rollout has
+//! two sides: *blue*, the value the tenant's own configuration gives, and
+//! *green*, the feature turned on. Tenants move from blue to green by
+//! cohort targeting or by a percentage rollout, and a kill switch sends
+//! everyone back to off at once.
+//!
+//! Percentage rollouts bucket tenants by a stable hash of the flag key and
+//! organization, so every workspace and project of an organization sees the
+//! same side, raising the percentage only ever adds organizations, and
+//! different flags roll out to different organizations first.
+//!
+//! Every change is recorded in an audit trail with the actor, the reason
+//! and the flag before and after.
+
+use dashmap::DashMap;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use thiserror::Error;
+use uuid::Uuid;
+
+use super::config::TenantConfig;
+use super::context::TenantId;
+
+/// Rollout errors
+#[derive(Error, Debug)]
+pub enum RolloutError {
+    /// No rollout for the flag
+    #[error("Flag rollout not found: {0}")]
+    NotFound(String),
+
+    /// A rollout for the flag already exists
+    #[error("Flag rollout already exists: {0}")]
+    AlreadyExists(String),
+
+    /// Percentage above 100
+    #[error("Rollout percentage must be 0-100, got {0}")]
+    InvalidPercentage(u8),
+}
+
+/// Result type for rollout operations
+pub type RolloutResult<T> = Result<T, RolloutError>;
+
+/// Group of tenants targeted by a rollout
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Cohort {
+    /// These tenants and everything beneath them
+    Tenants { tenants: Vec<TenantId> },
+    /// Tenants whose custom configuration value `key` equals `value`
+    Attribute { key: String, value: JsonValue },
+}
+
+impl Cohort {
+    /// Check if a tenant belongs to the cohort
+    pub fn matches(&self, tenant_id: &TenantId, config: &TenantConfig) -> bool {
+        match self {
+            Cohort::Tenants { tenants } => tenants
+                .iter()
+                .any(|t| t == tenant_id || t.is_ancestor_of(tenant_id)),
+            Cohort::Attribute { key, value } => config.custom.get(key) == Some(value),
+        }
+    }
+}
+
+/// Rollout state of one feature flag
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlagRollout {
+    /// Feature flag key, as passed to `is_feature_enabled`
+    pub key: String,
+    /// What the feature is
+    pub description: String,
+    /// Share of organizations on green, 0-100
+    pub percentage: u8,
+    /// Tenants on green regardless of percentage
+    pub cohorts: Vec<Cohort>,
+    /// Tenants (and their descendants) kept on blue regardless of cohorts
+    pub excluded: Vec<TenantId>,
+    /// Forces the feature off for every tenant
+    pub kill_switch: bool,
+    /// Incremented on every change
+    pub version: u32,
+    /// Last changed by
+    pub updated_by: String,
+    /// Last changed at
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl FlagRollout {
+    /// New rollout with no tenants on green
+    pub fn new(key: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            description: description.into(),
+            percentage: 0,
+            cohorts: Vec::new(),
+            excluded: Vec::new(),
+            kill_switch: false,
+            version: 1,
+            updated_by: String::new(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    /// Percentage bucket (0-99) of a tenant's organization for this flag
+    pub fn bucket(&self, tenant_id: &TenantId) -> u8 {
+        // FNV-1a: stable across processes and releases, unlike the std hasher
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in self.key.bytes().chain([b':']).chain(*tenant_id.org_id.as_bytes()) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        (hash % 100) as u8
+    }
+
+    /// Decide which side a tenant sees; `baseline` is the blue value
+    pub fn evaluate(&self, tenant_id: &TenantId, config: &TenantConfig, baseline: bool) -> FlagDecision {
+        let (enabled, reason) = if self.kill_switch {
+            (false, DecisionReason::KillSwitch)
+        } else if self
+            .excluded
+            .iter()
+            .any(|t| t == tenant_id || t.is_ancestor_of(tenant_id))
+        {
+            (baseline, DecisionReason::Excluded)
+        } else if self.cohorts.iter().any(|c| c.matches(tenant_id, config)) {
+            (true, DecisionReason::Cohort)
+        } else if self.bucket(tenant_id) < self.percentage {
+            (true, DecisionReason::Percentage)
+        } else {
+            (baseline, DecisionReason::Baseline)
+        };
+        FlagDecision { enabled, reason }
+    }
+}
+
+/// Why a flag evaluated the way it did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionReason {
+    /// No rollout is defined for the flag
+    NoRollout,
+    /// The kill switch is engaged
+    KillSwitch,
+    /// The tenant is excluded from the rollout
+    Excluded,
+    /// The tenant is in a targeted cohort
+    Cohort,
+    /// The tenant's organization falls within the rollout percentage
+    Percentage,
+    /// The tenant is not yet in the rollout
+    Baseline,
+}
+
+/// Result of evaluating a flag for a tenant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlagDecision {
+    /// Whether the feature is on
+    pub enabled: bool,
+    /// Why
+    pub reason: DecisionReason,
+}
+
+impl FlagDecision {
+    /// Whether the tenant is on the green side
+    pub fn is_green(&self) -> bool {
+        matches!(self.reason, DecisionReason::Cohort | DecisionReason::Percentage)
+    }
+}
+
+/// Partial update of a rollout; unset fields are left as they are
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlagUpdate {
+    /// New description
+    pub description: Option<String>,
+    /// New rollout percentage
+    pub percentage: Option<u8>,
+    /// Replacement cohorts
+    pub cohorts: Option<Vec<Cohort>>,
+    /// Replacement exclusions
+    pub excluded: Option<Vec<TenantId>>,
+}
+
+/// Kind of flag change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagChangeKind {
+    /// Rollout defined
+    Created,
+    /// Percentage, cohorts, exclusions or description changed
+    Updated,
+    /// Kill switch turned on
+    KillSwitchEngaged,
+    /// Kill switch turned off
+    KillSwitchReleased,
+    /// Rollout removed
+    Removed,
+}
+
+/// Audit record of a flag change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlagChange {
+    /// Change ID
+    pub id: Uuid,
+    /// Flag key
+    pub flag: String,
+    /// What happened
+    pub kind: FlagChangeKind,
+    /// Who made the change
+    pub actor: String,
+    /// Why, if given
+    pub reason: Option<String>,
+    /// Flag before the change; `None` when created
+    pub before: Option<FlagRollout>,
+    /// Flag after the change; `None` when removed
+    pub after: Option<FlagRollout>,
+    /// When
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+/// Feature flag rollout registry with change audit
+pub struct RolloutManager {
+    /// Rollouts by flag key
+    flags: DashMap<String, FlagRollout>,
+    /// Change history, oldest first
+    audit: RwLock<Vec<FlagChange>>,
+}
+
+impl RolloutManager {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self {
+            flags: DashMap::new(),
+            audit: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Define a rollout for a flag
+    pub fn create_flag(&self, mut flag: FlagRollout, actor: &str) -> RolloutResult<FlagRollout> {
+        validate_percentage(flag.percentage)?;
+        if self.flags.contains_key(&flag.key) {
+            return Err(RolloutError::AlreadyExists(flag.key));
+        }
+        flag.version = 1;
+        flag.updated_by = actor.to_string();
+        flag.updated_at = chrono::Utc::now();
+        self.flags.insert(flag.key.clone(), flag.clone());
+        self.record(FlagChangeKind::Created, actor, None, None, Some(flag.clone()));
+        Ok(flag)
+    }
+
+    /// Get a flag's rollout
+    pub fn get_flag(&self, key: &str) -> RolloutResult<FlagRollout> {
+        self.flags
+            .get(key)
+            .map(|f| f.clone())
+            .ok_or_else(|| RolloutError::NotFound(key.to_string()))
+    }
+
+    /// All rollouts, by key
+    pub fn list_flags(&self) -> Vec<FlagRollout> {
+        let mut flags: Vec<FlagRollout> = self.flags.iter().map(|f| f.clone()).collect();
+        flags.sort_by(|a, b| a.key.cmp(&b.key));
+        flags
+    }
+
+    /// Change a rollout's percentage, cohorts, exclusions or description
+    pub fn update_flag(&self, key: &str, update: FlagUpdate, actor: &str, reason: Option<String>) -> RolloutResult<FlagRollout> {
+        if let Some(percentage) = update.percentage {
+            validate_percentage(percentage)?;
+        }
+        self.modify(key, FlagChangeKind::Updated, actor, reason, |flag| {
+            if let Some(description) = update.description {
+                flag.description = description;
+            }
+            if let Some(percentage) = update.percentage {
+                flag.percentage = percentage;
+            }
+            if let Some(cohorts) = update.cohorts {
+                flag.cohorts = cohorts;
+            }
+            if let Some(excluded) = update.excluded {
+                flag.excluded = excluded;
+            }
+        })
+    }
+
+    /// Turn the feature off for every tenant
+    pub fn engage_kill_switch(&self, key: &str, actor: &str, reason: Option<String>) -> RolloutResult<FlagRollout> {
+        self.modify(key, FlagChangeKind::KillSwitchEngaged, actor, reason, |flag| {
+            flag.kill_switch = true;
+        })
+    }
+
+    /// Resume the rollout where it was before the kill switch
+    pub fn release_kill_switch(&self, key: &str, actor: &str, reason: Option<String>) -> RolloutResult<FlagRollout> {
+        self.modify(key, FlagChangeKind::KillSwitchReleased, actor, reason, |flag| {
+            flag.kill_switch = false;
+        })
+    }
+
+    /// Remove a rollout; tenants go back to their configured value
+    pub fn remove_flag(&self, key: &str, actor: &str, reason: Option<String>) -> RolloutResult<FlagRollout> {
+        let (_, flag) = self
+            .flags
+            .remove(key)
+            .ok_or_else(|| RolloutError::NotFound(key.to_string()))?;
+        self.record(FlagChangeKind::Removed, actor, reason, Some(flag.clone()), None);
+        Ok(flag)
+    }
+
+    /// Evaluate a flag for a tenant; `baseline` is the tenant's configured value
+    pub fn evaluate(&self, key: &str, tenant_id: &TenantId, config: &TenantConfig, baseline: bool) -> FlagDecision {
+        match self.flags.get(key) {
+            Some(flag) => flag.evaluate(tenant_id, config, baseline),
+            None => FlagDecision {
+                enabled: baseline,
+                reason: DecisionReason::NoRollout,
+            },
+        }
+    }
+
+    /// Change history, newest first, optionally for one flag
+    pub fn audit_log(&self, flag: Option<&str>) -> Vec<FlagChange> {
+        self.audit
+            .read()
+            .iter()
+            .rev()
+            .filter(|c| flag.is_none_or(|f| c.flag == f))
+            .cloned()
+            .collect()
+    }
+
+    fn modify(
+        &self,
+        key: &str,
+        kind: FlagChangeKind,
+        actor: &str,
+        reason: Option<String>,
+        change: impl FnOnce(&mut FlagRollout),
+    ) -> RolloutResult<FlagRollout> {
+        let mut entry = self
+            .flags
+            .get_mut(key)
+            .ok_or_else(|| RolloutError::NotFound(key.to_string()))?;
+        let before = entry.clone();
+        change(&mut entry);
+        entry.version += 1;
+        entry.updated_by = actor.to_string();
+        entry.updated_at = chrono::Utc::now();
+        let after = entry.clone();
+        drop(entry);
+
+        self.record(kind, actor, reason, Some(before), Some(after.clone()));
+        Ok(after)
+    }
+
+    fn record(
+        &self,
+        kind: FlagChangeKind,
+        actor: &str,
+        reason: Option<String>,
+        before: Option<FlagRollout>,
+        after: Option<FlagRollout>,
+    ) {
+        let flag = after
+            .as_ref()
+            .or(before.as_ref())
+            .map(|f| f.key.clone())
+            .unwrap_or_default();
+        self.audit.write().push(FlagChange {
+            id: Uuid::new_v4(),
+            flag,
+            kind,
+            actor: actor.to_string(),
+            reason,
+            before,
+            after,
+            timestamp: chrono::Utc::now(),
+        });
+    }
+}
+
+impl Default for RolloutManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn validate_percentage(percentage: u8) -> RolloutResult<()> {
+    if percentage > 100 {
+        return Err(RolloutError::InvalidPercentage(percentage));
+    }
+    Ok(())
+}
+
+/// Share of a set of tenants a rollout currently puts on green
+pub fn green_share(flag: &FlagRollout, tenants: &[(TenantId, TenantConfig)]) -> f64 {
+    if tenants.is_empty() {
+        return 0.0;
+    }
+    let green = tenants
+        .iter()
+        .filter(|(id, config)| flag.evaluate(id, config, false).is_green())
+        .count();
+    green as f64 / tenants.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tenants(n: usize) -> Vec<(TenantId, TenantConfig)> {
+        (0..n)
+            .map(|_| {
+                let id = TenantId::new_org(Uuid::new_v4());
+                (id.clone(), TenantConfig::new(id))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_percentage_rollout_is_stable_and_monotonic() {
+        let tenants = tenants(2000);
+        let mut flag = FlagRollout::new("ai_features", "AI assistant");
+        assert_eq!(green_share(&flag, &tenants), 0.0);
+
+        flag.percentage = 25;
+        let quarter: Vec<bool> = tenants
+            .iter()
+            .map(|(id, c)| flag.evaluate(id, c, false).enabled)
+            .collect();
+        let share = green_share(&flag, &tenants);
+        assert!((share - 0.25).abs() < 0.05, "share {}", share);
+
+        // Raising the percentage keeps everyone already on green
+        flag.percentage = 50;
+        for ((id, c), was) in tenants.iter().zip(&quarter) {
+            let now = flag.evaluate(id, c, false).enabled;
+            assert!(!was || now);
+        }
+
+        // Workspaces follow their organization
+        let (org, config) = &tenants[0];
+        let ws = TenantId::new_workspace(org.org_id, Uuid::new_v4());
+        assert_eq!(flag.bucket(org), flag.bucket(&ws));
+        assert_eq!(
+            flag.evaluate(org, config, false),
+            flag.evaluate(&ws, config, false)
+        );
+
+        flag.percentage = 100;
+        assert_eq!(green_share(&flag, &tenants), 1.0);
+    }
+
+    #[test]
+    fn test_cohorts_exclusions_and_kill_switch() {
+        let manager = RolloutManager::new();
+        let beta = TenantId::new_org(Uuid::new_v4());
+        let excluded = TenantId::new_org(Uuid::new_v4());
+        let tagged = TenantId::new_org(Uuid::new_v4());
+        let other = TenantId::new_org(Uuid::new_v4());
+        let mut tagged_config = TenantConfig::new(tagged.clone());
+        tagged_config.set_custom("region".to_string(), "eu").unwrap();
+        let config = TenantConfig::new(other.clone());
+
+        manager.create_flag(FlagRollout::new("workflows", "Workflow engine"), "ops").unwrap();
+        assert!(matches!(
+            manager.create_flag(FlagRollout::new("workflows", ""), "ops"),
+            Err(RolloutError::AlreadyExists(_))
+        ));
+        manager
+            .update_flag(
+                "workflows",
+                FlagUpdate {
+                    cohorts: Some(vec![
+                        Cohort::Tenants { tenants: vec![beta.clone(), excluded.clone()] },
+                        Cohort::Attribute { key: "region".to_string(), value: "eu".into() },
+                    ]),
+                    excluded: Some(vec![excluded.clone()]),
+                    ..FlagUpdate::default()
+                },
+                "ops",
+                Some("beta cohort".to_string()),
+            )
+            .unwrap();
+
+        let project = TenantId::new_project(beta.org_id, Uuid::new_v4(), Uuid::new_v4());
+        let decision = manager.evaluate("workflows", &project, &config, false);
+        assert_eq!(decision, FlagDecision { enabled: true, reason: DecisionReason::Cohort });
+        assert!(manager.evaluate("workflows", &tagged, &tagged_config, false).enabled);
+        assert_eq!(
+            manager.evaluate("workflows", &excluded, &config, false).reason,
+            DecisionReason::Excluded
+        );
+        // Outside every cohort the tenant keeps its configured value
+        assert_eq!(
+            manager.evaluate("workflows", &other, &config, true),
+            FlagDecision { enabled: true, reason: DecisionReason::Baseline }
+        );
+        assert_eq!(
+            manager.evaluate("unknown", &other, &config, true).reason,
+            DecisionReason::NoRollout
+        );
+        assert!(matches!(
+            manager.update_flag("workflows", FlagUpdate { percentage: Some(101), ..FlagUpdate::default() }, "ops", None),
+            Err(RolloutError::InvalidPercentage(101))
+        ));
+
+        // The kill switch overrides cohorts and configuration alike
+        manager.engage_kill_switch("workflows", "oncall", Some("incident".to_string())).unwrap();
+        assert!(!manager.evaluate("workflows", &beta, &config, true).enabled);
+        let flag = manager.release_kill_switch("workflows", "oncall", None).unwrap();
+        assert!(manager.evaluate("workflows", &beta, &config, false).enabled);
+        assert_eq!(flag.version, 4);
+
+        let log = manager.audit_log(Some("workflows"));
+        let kinds: Vec<FlagChangeKind> = log.iter().map(|c| c.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                FlagChangeKind::KillSwitchReleased,
+                FlagChangeKind::KillSwitchEngaged,
+                FlagChangeKind::Updated,
+                FlagChangeKind::Created,
+            ]
+        );
+        assert_eq!(log[1].actor, "oncall");
+        assert_eq!(log[1].reason.as_deref(), Some("incident"));
+        assert!(!log[1].before.as_ref().unwrap().kill_switch);
+        assert!(log[1].after.as_ref().unwrap().kill_switch);
+
+        manager.remove_flag("workflows", "ops", None).unwrap();
+        assert!(manager.get_flag("workflows").is_err());
+        assert_eq!(manager.audit_log(None)[0].kind, FlagChangeKind::Removed);
+    }
+}
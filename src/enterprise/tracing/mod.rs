@@ -7,6 +7,8 @@
 //!
 //! - **Distributed Tracing**: W3C Trace Context compliant span management with parent-child
 //!   relationships, baggage propagation, and context injection/extraction.
+//!   Webhook, CI integration and notification deliveries carry the caller's
+//!   trace context downstream and record a span per delivery.
 //!
 //! - **Multi-Format Export**: Support for OpenTelemetry Protocol (OTLP), Jaeger, Zipkin,
 //!   and console output for flexible backend integration.
@@ -375,6 +377,7 @@
 //! ├── correlation.rs   - Log-to-trace correlation
 //! ├── sampler.rs       - Sampling strategies
 //! ├── profiler.rs      - CPU and memory profiling
+//! ├── propagation.rs   - Trace context in outbound webhook and integration calls
 //! └── mod.rs           - Module documentation (this file)
 //! ```
 //!
@@ -425,6 +428,9 @@ pub mod sampler;
 /// Performance profiling
 pub mod profiler;
 
+/// Trace context propagation into outbound deliveries
+pub mod propagation;
+
 // ============================================================================
 // Re-exports for convenience
 // ============================================================================
@@ -457,6 +463,11 @@ pub use sampler::{
     CompositeMode, AdaptiveSampler,
 };
 
+pub use propagation::{
+    current_context, delivery_spans, in_context, DeliverySpan, TracedSend,
+    TRACEPARENT_HEADER, TRACESTATE_HEADER,
+};
+
 pub use profiler::{
     CpuProfiler, MemoryProfiler, ContinuousProfiler,
     ProfileScope, ProfileStats, MemoryStats, ProfilingReport,
@@ -0,0 +1,250 @@
+//! Trace context propagation into outbound deliveries
+//!
+//! Inbound API requests run inside [`in_context`], which makes their span
+//! context available to everything awaited on the same task through
+//! [`current_context`]. Outbound deliveries (webhooks, CI status updates,
+//! notifications) start a [`DeliverySpan`] as a child of that context,
+//! carry it downstream in `traceparent`/`tracestate` headers and, once the
+//! response is in, record the status code and latency. Finished delivery
+//! spans are kept in [`delivery_spans`] until an exporter collects them.
+
+use std::future::Future;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+
+use super::span::{AttributeValue, Span, SpanContext, SpanKind, SpanStatus, SpanStore, TraceState};
+
+/// W3C trace context header
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// W3C vendor trace state header
+pub const TRACESTATE_HEADER: &str = "tracestate";
+
+/// Most finished delivery spans held at once; further spans are dropped
+/// until the held ones are collected
+pub const MAX_PENDING_DELIVERY_SPANS: usize = 10_000;
+
+tokio::task_local! {
+    static CURRENT_CONTEXT: SpanContext;
+}
+
+static DELIVERY_SPANS: Lazy<SpanStore> = Lazy::new(SpanStore::new);
+
+/// Run `future` with `context` as the current trace context
+pub async fn in_context<F: Future>(context: SpanContext, future: F) -> F::Output {
+    CURRENT_CONTEXT.scope(context, future).await
+}
+
+/// Trace context of the enclosing [`in_context`] scope, if any
+///
+/// Task-local, so it does not follow `tokio::spawn`; capture it before
+/// spawning and pass it to the new task.
+pub fn current_context() -> Option<SpanContext> {
+    CURRENT_CONTEXT.try_with(|context| context.clone()).ok()
+}
+
+/// Parse inbound `traceparent` and `tracestate` header values
+pub fn extract(traceparent: Option<&str>, tracestate: Option<&str>) -> Option<SpanContext> {
+    let mut context = SpanContext::from_traceparent(traceparent?.trim()).ok()?;
+    if let Some(state) = tracestate {
+        context.trace_state = TraceState::from_header(state);
+    }
+    Some(context)
+}
+
+/// Headers carrying `context` to a downstream service
+pub fn inject(context: &SpanContext) -> Vec<(&'static str, String)> {
+    let mut headers = vec![(TRACEPARENT_HEADER, context.to_traceparent())];
+    let state = context.trace_state.to_header();
+    if !state.is_empty() {
+        headers.push((TRACESTATE_HEADER, state));
+    }
+    headers
+}
+
+/// Store holding finished delivery spans until they are exported
+pub fn delivery_spans() -> &'static SpanStore {
+    &DELIVERY_SPANS
+}
+
+/// Client span around one outbound delivery attempt
+pub struct DeliverySpan {
+    span: Span,
+    started: Instant,
+}
+
+impl DeliverySpan {
+    /// Start a delivery span under `parent`, or a new trace without one
+    pub fn start(name: impl Into<String>, parent: Option<&SpanContext>) -> Self {
+        let mut span = Span::new(name).with_kind(SpanKind::Client);
+        if let Some(parent) = parent {
+            span.context = parent.child();
+        }
+        Self {
+            span,
+            started: Instant::now(),
+        }
+    }
+
+    /// Start a delivery span under the current trace context
+    pub fn start_current(name: impl Into<String>) -> Self {
+        Self::start(name, current_context().as_ref())
+    }
+
+    /// Context propagated downstream
+    pub fn context(&self) -> &SpanContext {
+        &self.span.context
+    }
+
+    /// Headers to add to the outbound request
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        inject(&self.span.context)
+    }
+
+    /// Add an attribute
+    pub fn set_attribute(&mut self, key: impl Into<String>, value: impl Into<AttributeValue>) {
+        self.span.set_attribute(key, value);
+    }
+
+    /// End the span with the response status (or transport error) and
+    /// latency, and keep it in [`delivery_spans`]
+    pub fn finish(mut self, status_code: Option<u16>, error: Option<&str>) -> Span {
+        let latency_ms = self.started.elapsed().as_millis() as i64;
+        self.span.set_attribute("delivery.latency_ms", latency_ms);
+        if let Some(code) = status_code {
+            self.span.set_attribute("http.status_code", code as i64);
+        }
+        if let Some(error) = error {
+            self.span.set_attribute("error", true);
+            self.span.set_attribute("error.message", error);
+        }
+        let succeeded = error.is_none() && status_code.is_some_and(|code| (200..300).contains(&code));
+        self.span.set_status(if succeeded { SpanStatus::Ok } else { SpanStatus::Error });
+        self.span.end();
+
+        if DELIVERY_SPANS.len() < MAX_PENDING_DELIVERY_SPANS {
+            DELIVERY_SPANS.add(self.span.clone());
+        } else {
+            log::debug!("Delivery span buffer full, dropping span {}", self.span.name);
+        }
+        self.span
+    }
+}
+
+/// Traced sending for outbound HTTP requests
+#[async_trait]
+pub trait TracedSend {
+    /// Send under a [`DeliverySpan`] named `operation`, a child of the
+    /// current trace context
+    async fn send_traced(self, operation: &str) -> reqwest::Result<reqwest::Response>;
+}
+
+#[async_trait]
+impl TracedSend for reqwest::RequestBuilder {
+    async fn send_traced(self, operation: &str) -> reqwest::Result<reqwest::Response> {
+        let mut delivery = DeliverySpan::start_current(operation);
+        let mut request = self;
+        for (name, value) in delivery.headers() {
+            request = request.header(name, value);
+        }
+
+        let result = request.send().await;
+        match &result {
+            Ok(response) => {
+                delivery.set_attribute("http.url", response.url().to_string());
+                delivery.finish(Some(response.status().as_u16()), None);
+            }
+            Err(err) => {
+                if let Some(url) = err.url() {
+                    delivery.set_attribute("http.url", url.to_string());
+                }
+                delivery.finish(err.status().map(|s| s.as_u16()), Some(&err.to_string()));
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_extract_and_inject_round_trip() {
+        let mut context = SpanContext::new_root();
+        context.trace_state.insert("caddy".to_string(), "t1".to_string());
+
+        let headers = inject(&context);
+        assert_eq!(headers[0].0, TRACEPARENT_HEADER);
+        assert_eq!(headers[1], (TRACESTATE_HEADER, "caddy=t1".to_string()));
+
+        let parsed = extract(Some(&headers[0].1), Some(&headers[1].1)).unwrap();
+        assert_eq!(parsed.trace_id, context.trace_id);
+        assert_eq!(parsed.span_id, context.span_id);
+        assert_eq!(parsed.trace_state.get("caddy"), Some(&"t1".to_string()));
+
+        assert!(extract(Some("not-a-traceparent"), None).is_none());
+        assert!(extract(None, Some("caddy=t1")).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delivery_span_is_child_of_current_context() {
+        let inbound = SpanContext::new_root();
+        assert!(current_context().is_none());
+
+        let span = in_context(inbound.clone(), async {
+            DeliverySpan::start_current("webhook.deliver").finish(Some(503), None)
+        })
+        .await;
+
+        assert_eq!(span.kind, SpanKind::Client);
+        assert_eq!(span.context.trace_id, inbound.trace_id);
+        assert_eq!(span.context.parent_span_id, Some(inbound.span_id));
+        assert_eq!(span.status, SpanStatus::Error);
+        assert!(span.attributes.contains_key("delivery.latency_ms"));
+        assert!(delivery_spans().get(&span.context.span_id).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_send_traced_injects_traceparent() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let read = socket.read(&mut buffer).await.unwrap();
+            socket
+                .write_all(b"HTTP/1.1 202 Accepted\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&buffer[..read]).to_lowercase()
+        });
+
+        let inbound = SpanContext::new_root();
+        let response = in_context(
+            inbound.clone(),
+            reqwest::Client::new()
+                .post(format!("http://{}/hook", address))
+                .send_traced("test.deliver"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status().as_u16(), 202);
+
+        let request = server.await.unwrap();
+        let traceparent = request
+            .lines()
+            .find_map(|line| line.strip_prefix("traceparent: "))
+            .unwrap()
+            .to_string();
+        let sent = SpanContext::from_traceparent(&traceparent).unwrap();
+        assert_eq!(sent.trace_id, inbound.trace_id);
+
+        let span = delivery_spans().get(&sent.span_id).unwrap();
+        assert_eq!(span.status, SpanStatus::Ok);
+        assert_eq!(span.context.parent_span_id, Some(inbound.span_id));
+    }
+}
@@ -5,7 +5,7 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -518,6 +518,16 @@ impl SpanStore {
         self.spans.write().remove(span_id)
     }
 
+    /// Number of stored spans
+    pub fn len(&self) -> usize {
+        self.spans.read().len()
+    }
+
+    /// Whether the store is empty
+    pub fn is_empty(&self) -> bool {
+        self.spans.read().is_empty()
+    }
+
     /// Get all spans
     pub fn all(&self) -> Vec<Span> {
         self.spans.read().values().cloned().collect()
@@ -5,6 +5,7 @@
 
 use super::mesh::{TriangleMesh, TriangleFace, Vertex};
 use super::solid::BoundingBox;
+use crate::core::precision::{orient3d, PredicateMode};
 use nalgebra::{Point3, Vector3};
 use serde::{Deserialize, Serialize};
 
@@ -156,6 +157,7 @@ impl CSGNode {
 /// CSG operator for performing boolean operations on meshes
 pub struct CSGOperator {
     tolerance: f64,
+    predicates: PredicateMode,
 }
 
 impl CSGOperator {
@@ -163,12 +165,22 @@ impl CSGOperator {
     pub fn new() -> Self {
         Self {
             tolerance: 1e-10,
+            predicates: PredicateMode::Tolerance,
         }
     }
 
     /// Creates a CSG operator with custom tolerance
     pub fn with_tolerance(tolerance: f64) -> Self {
-        Self { tolerance }
+        Self {
+            tolerance,
+            predicates: PredicateMode::Tolerance,
+        }
+    }
+
+    /// Classify vertices against splitting planes with `mode`
+    pub fn with_predicates(mut self, mode: PredicateMode) -> Self {
+        self.predicates = mode;
+        self
     }
 
     /// Performs union operation (A ∪ B)
@@ -195,8 +207,8 @@ impl CSGOperator {
         operation: BooleanOperation,
     ) -> TriangleMesh {
         // Build BSP tree from mesh A
-        let mut bsp_a = BSPTree::from_mesh(a);
-        let mut bsp_b = BSPTree::from_mesh(b);
+        let mut bsp_a = BSPTree::from_mesh(a, self.predicates);
+        let mut bsp_b = BSPTree::from_mesh(b, self.predicates);
 
         // Classify and clip meshes
         match operation {
@@ -303,19 +315,48 @@ struct BSPPolygon {
 struct BSPPlane {
     normal: Vector3<f64>,
     w: f64,
+    /// Defining points, counter-clockwise seen from the front, for exact
+    /// classification
+    points: [Point3<f64>; 3],
+    predicates: PredicateMode,
 }
 
 impl BSPPlane {
-    fn from_points(p1: &Point3<f64>, p2: &Point3<f64>, p3: &Point3<f64>) -> Self {
+    fn from_points(p1: &Point3<f64>, p2: &Point3<f64>, p3: &Point3<f64>, predicates: PredicateMode) -> Self {
         let v1 = p2 - p1;
         let v2 = p3 - p1;
         let normal = v1.cross(&v2).normalize();
         let w = normal.dot(&p1.coords);
 
-        Self { normal, w }
+        Self {
+            normal,
+            w,
+            points: [*p1, *p2, *p3],
+            predicates,
+        }
+    }
+
+    /// Turn the plane around
+    fn flip(&mut self) {
+        self.normal = -self.normal;
+        self.w = -self.w;
+        self.points.swap(1, 2);
     }
 
     fn classify_point(&self, point: &Point3<f64>) -> i32 {
+        if self.predicates == PredicateMode::Exact {
+            let [a, b, c] = self.points.map(|p| [p.x, p.y, p.z]);
+            // Positive below the plane, i.e. behind it
+            let side = orient3d(a, b, c, [point.x, point.y, point.z]);
+            return if side > 0.0 {
+                -1
+            } else if side < 0.0 {
+                1
+            } else {
+                0
+            };
+        }
+
         let t = self.normal.dot(&point.coords) - self.w;
         const EPSILON: f64 = 1e-10;
 
@@ -384,7 +425,7 @@ impl BSPTree {
         }
     }
 
-    fn from_mesh(mesh: &TriangleMesh) -> Self {
+    fn from_mesh(mesh: &TriangleMesh, predicates: PredicateMode) -> Self {
         let mut tree = Self::new();
 
         let polygons: Vec<BSPPolygon> = mesh
@@ -395,7 +436,7 @@ impl BSPTree {
                 let v1 = mesh.vertices[face.vertices[1]];
                 let v2 = mesh.vertices[face.vertices[2]];
 
-                let plane = BSPPlane::from_points(&v0.position, &v1.position, &v2.position);
+                let plane = BSPPlane::from_points(&v0.position, &v1.position, &v2.position, predicates);
 
                 BSPPolygon {
                     vertices: vec![v0, v1, v2],
@@ -486,13 +527,11 @@ impl BSPTree {
     fn invert(&mut self) {
         for polygon in &mut self.polygons {
             polygon.vertices.reverse();
-            polygon.plane.normal = -polygon.plane.normal;
-            polygon.plane.w = -polygon.plane.w;
+            polygon.plane.flip();
         }
 
         if let Some(plane) = &mut self.plane {
-            plane.normal = -plane.normal;
-            plane.w = -plane.w;
+            plane.flip();
         }
 
         std::mem::swap(&mut self.front, &mut self.back);
@@ -635,10 +674,14 @@ mod tests {
         let p2 = Point3::new(1.0, 0.0, 0.0);
         let p3 = Point3::new(0.0, 1.0, 0.0);
 
-        let plane = BSPPlane::from_points(&p1, &p2, &p3);
+        let plane = BSPPlane::from_points(&p1, &p2, &p3, PredicateMode::Tolerance);
 
         assert_eq!(plane.classify_point(&Point3::new(0.5, 0.5, 1.0)), 1);
         assert_eq!(plane.classify_point(&Point3::new(0.5, 0.5, -1.0)), -1);
+        assert_eq!(plane.classify_point(&Point3::new(0.5, 0.5, 1e-12)), 0);
+
+        let exact = BSPPlane::from_points(&p1, &p2, &p3, PredicateMode::Exact);
+        assert_eq!(exact.classify_point(&Point3::new(0.5, 0.5, 1e-12)), 1);
     }
 
     #[test]
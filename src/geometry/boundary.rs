@@ -0,0 +1,611 @@
+//! Boundary detection from a pick point
+//!
+//! [`boundary_at`] finds the smallest closed region around a pick point that
+//! a set of curves encloses, as for hatching by picking inside an area,
+//! creating a region or querying an area. The curves are split wherever
+//! they meet and their ends joined where they come within the tolerance of
+//! each other, giving a planar graph. Ends that lead nowhere are pruned, and
+//! the faces of the graph are traced by turning as far right as possible at
+//! every vertex, which walks each bounded face counterclockwise. The region
+//! is the face of least area containing the pick.
+//!
+//! Groups of curves inside the region that touch nothing on its boundary
+//! are islands: the outline of each outermost one becomes a hole of the
+//! region, wound clockwise. Islands within those holes are left to a second
+//! pick, as in AutoCAD's normal island detection. An island joined to the
+//! boundary by a single bridging curve is traced along both sides of the
+//! bridge as part of the outer loop instead.
+//!
+//! Lines and circular arcs stay exact; other curves are replaced by biarcs
+//! within the tolerance first, so loops come out as bulged polylines.
+
+use crate::core::precision::{EPSILON, EPSILON_ROUGH};
+use crate::core::primitives::{BoundingBox3, Point3};
+use crate::geometry::arc::Arc2D;
+use crate::geometry::convert::{biarcs, flatten};
+use crate::geometry::fitting::{ArcPolyline, FitSegment};
+use crate::geometry::intersect::{intersect_with_tolerance, CurveRef};
+use crate::geometry::line::LineSegment2D;
+use crate::geometry::mass::AreaProperties;
+use crate::geometry::point::Point2D;
+use crate::geometry::polygon::Polygon2D;
+use crate::geometry::spatial::SpatialIndex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::{PI, TAU};
+
+/// Tangent directions closer than this (radians) are ordered by curvature
+const ANGLE_TOLERANCE: f64 = 1e-9;
+
+/// Boundary detection settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundaryOptions {
+    /// Gaps up to this size are closed, and curves other than lines and
+    /// arcs are approximated to within it
+    pub tolerance: f64,
+    /// Cut islands inside the region out of it
+    pub detect_islands: bool,
+}
+
+impl Default for BoundaryOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: EPSILON_ROUGH,
+            detect_islands: true,
+        }
+    }
+}
+
+/// A closed region: an outer loop less the islands inside it
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Region {
+    /// Outer boundary, counterclockwise
+    pub outer: ArcPolyline,
+    /// Outlines of the islands, clockwise
+    pub islands: Vec<ArcPolyline>,
+}
+
+impl Region {
+    /// Area inside the outer loop and outside the islands
+    pub fn area(&self) -> f64 {
+        let island_area: f64 = self
+            .islands
+            .iter()
+            .map(|island| signed_area(&island.segments()).abs())
+            .sum();
+        signed_area(&self.outer.segments()).abs() - island_area
+    }
+
+    /// Length of all the loops together
+    pub fn perimeter(&self) -> f64 {
+        self.outer.length() + self.islands.iter().map(ArcPolyline::length).sum::<f64>()
+    }
+
+    /// The loops as polygons whose chords stay within `tolerance` of them,
+    /// outer loop first, as hatching takes them
+    pub fn loops(&self, tolerance: f64) -> Vec<Vec<Point2D>> {
+        std::iter::once(&self.outer)
+            .chain(&self.islands)
+            .map(|l| flatten_loop(&l.segments(), tolerance))
+            .collect()
+    }
+
+    /// Area, centroid and second moments of area, with arcs flattened
+    /// within `tolerance`
+    pub fn area_properties(&self, tolerance: f64) -> Option<AreaProperties> {
+        AreaProperties::of_polygon(&self.to_polygon(tolerance))
+    }
+
+    /// The region as a polygon with holes, flattened within `tolerance`
+    pub fn to_polygon(&self, tolerance: f64) -> Polygon2D {
+        let mut loops = self.loops(tolerance);
+        let outer = loops.remove(0);
+        Polygon2D::with_holes(outer, loops)
+    }
+}
+
+/// The smallest region around `pick` enclosed by `curves`, or `None` if the
+/// pick is not enclosed
+pub fn boundary_at(curves: &[CurveRef], pick: Point2D, options: &BoundaryOptions) -> Option<Region> {
+    let tolerance = options.tolerance.max(EPSILON);
+    let pieces: Vec<FitSegment> = curves
+        .iter()
+        .flat_map(|curve| pieces_of(curve, tolerance))
+        .filter(|piece| piece.length() > tolerance)
+        .collect();
+    let graph = Graph::new(split_at_crossings(&pieces, tolerance), tolerance);
+    let faces = graph.faces(tolerance);
+
+    let region = faces
+        .iter()
+        .filter(|face| face.area > tolerance * tolerance && face.polygon.contains_point(&pick))
+        .min_by(|a, b| a.area.total_cmp(&b.area))?;
+
+    let mut islands: Vec<&Face> = Vec::new();
+    if options.detect_islands {
+        // Each other component's outline: its one face winding clockwise
+        let mut outlines: HashMap<usize, &Face> = HashMap::new();
+        for face in faces
+            .iter()
+            .filter(|face| face.component != region.component && face.area < 0.0)
+        {
+            let outline = outlines.entry(face.component).or_insert(face);
+            if face.area < outline.area {
+                *outline = face;
+            }
+        }
+        let inside: Vec<&Face> = outlines
+            .into_values()
+            .filter(|outline| region.polygon.contains_point(&outline.polygon.vertices[0]))
+            .collect();
+        islands = inside
+            .iter()
+            .filter(|island| {
+                !inside.iter().any(|other| {
+                    other.component != island.component && other.polygon.contains_point(&island.polygon.vertices[0])
+                })
+            })
+            .copied()
+            .collect();
+        islands.sort_by_key(|island| island.component);
+    }
+
+    Some(Region {
+        outer: ArcPolyline::from_segments(&region.segments, true),
+        islands: islands
+            .into_iter()
+            .map(|island| ArcPolyline::from_segments(&island.segments, true))
+            .collect(),
+    })
+}
+
+/// Lines and arcs making up a curve
+fn pieces_of(curve: &CurveRef, tolerance: f64) -> Vec<FitSegment> {
+    match curve {
+        CurveRef::Segment(line) => vec![FitSegment::Line(**line)],
+        CurveRef::Arc(arc) => vec![FitSegment::Arc(**arc)],
+        CurveRef::Circle(circle) => vec![
+            FitSegment::Arc(Arc2D::new(circle.center, circle.radius, 0.0, PI, true)),
+            FitSegment::Arc(Arc2D::new(circle.center, circle.radius, PI, TAU, true)),
+        ],
+        _ => biarcs(*curve, tolerance),
+    }
+}
+
+fn curve_of(segment: &FitSegment) -> CurveRef<'_> {
+    match segment {
+        FitSegment::Line(line) => line.into(),
+        FitSegment::Arc(arc) => arc.into(),
+    }
+}
+
+fn bounds(segment: &FitSegment, tolerance: f64) -> BoundingBox3 {
+    let (min, max) = match segment {
+        FitSegment::Line(line) => (
+            Point2D::new(line.start.x.min(line.end.x), line.start.y.min(line.end.y)),
+            Point2D::new(line.start.x.max(line.end.x), line.start.y.max(line.end.y)),
+        ),
+        FitSegment::Arc(arc) => {
+            let bounds = arc.bounding_box();
+            (
+                Point2D::new(bounds.min.x, bounds.min.y),
+                Point2D::new(bounds.max.x, bounds.max.y),
+            )
+        }
+    };
+    BoundingBox3::new(
+        Point3::new(min.x - tolerance, min.y - tolerance, 0.0),
+        Point3::new(max.x + tolerance, max.y + tolerance, 0.0),
+    )
+}
+
+/// Split every piece wherever another piece meets it
+fn split_at_crossings(pieces: &[FitSegment], tolerance: f64) -> Vec<FitSegment> {
+    let index = SpatialIndex::from_items(
+        pieces
+            .iter()
+            .enumerate()
+            .map(|(i, piece)| (i, bounds(piece, tolerance))),
+    );
+    let mut cuts: Vec<Vec<f64>> = vec![Vec::new(); pieces.len()];
+    for (i, piece) in pieces.iter().enumerate() {
+        for j in index.query_box(&bounds(piece, tolerance)) {
+            if j <= i {
+                continue;
+            }
+            for hit in intersect_with_tolerance(curve_of(piece), curve_of(&pieces[j]), tolerance) {
+                cuts[i].push(hit.param_a);
+                cuts[j].push(hit.param_b);
+            }
+        }
+    }
+    pieces
+        .iter()
+        .zip(cuts)
+        .flat_map(|(piece, cuts)| split(piece, cuts, tolerance))
+        .collect()
+}
+
+/// A line or arc cut at parameters of its [`CurveRef`] form
+fn split(piece: &FitSegment, mut cuts: Vec<f64>, tolerance: f64) -> Vec<FitSegment> {
+    let (end, scale) = match piece {
+        FitSegment::Line(line) => (1.0, line.length()),
+        FitSegment::Arc(arc) => (arc.sweep_angle(), arc.radius),
+    };
+    let gap = tolerance / scale;
+    cuts.sort_by(f64::total_cmp);
+    let mut params = vec![0.0];
+    for t in cuts {
+        if t > params[params.len() - 1] + gap && t < end - gap {
+            params.push(t);
+        }
+    }
+    params.push(end);
+
+    params
+        .windows(2)
+        .map(|span| match piece {
+            FitSegment::Line(line) => {
+                FitSegment::Line(LineSegment2D::new(line.point_at(span[0]), line.point_at(span[1])))
+            }
+            FitSegment::Arc(arc) => {
+                let angle = |t: f64| {
+                    if arc.ccw {
+                        arc.start_angle + t
+                    } else {
+                        arc.start_angle - t
+                    }
+                };
+                FitSegment::Arc(Arc2D::new(
+                    arc.center,
+                    arc.radius,
+                    angle(span[0]),
+                    angle(span[1]),
+                    arc.ccw,
+                ))
+            }
+        })
+        .collect()
+}
+
+fn reverse(segment: &FitSegment) -> FitSegment {
+    match segment {
+        FitSegment::Line(line) => FitSegment::Line(LineSegment2D::new(line.end, line.start)),
+        FitSegment::Arc(arc) => FitSegment::Arc(arc.reverse()),
+    }
+}
+
+/// Direction a segment leaves its start in, and its curvature to the left
+fn departure(segment: &FitSegment) -> (f64, f64) {
+    match segment {
+        FitSegment::Line(line) => (
+            (line.end.y - line.start.y)
+                .atan2(line.end.x - line.start.x)
+                .rem_euclid(TAU),
+            0.0,
+        ),
+        FitSegment::Arc(arc) => {
+            let turn = if arc.ccw { PI / 2.0 } else { -PI / 2.0 };
+            let curvature = if arc.ccw { 1.0 } else { -1.0 } / arc.radius;
+            ((arc.start_angle + turn).rem_euclid(TAU), curvature)
+        }
+    }
+}
+
+/// Signed area enclosed by a loop of segments, positive counterclockwise
+fn signed_area(segments: &[FitSegment]) -> f64 {
+    segments
+        .iter()
+        .map(|segment| {
+            let (start, end) = (segment.start(), segment.end());
+            let chord = start.cross(&end) / 2.0;
+            match segment {
+                FitSegment::Line(_) => chord,
+                FitSegment::Arc(arc) => {
+                    let sweep = if arc.ccw { arc.sweep_angle() } else { -arc.sweep_angle() };
+                    chord + arc.radius * arc.radius * (sweep - sweep.sin()) / 2.0
+                }
+            }
+        })
+        .sum()
+}
+
+/// A loop's vertices, with arcs replaced by chords within `tolerance`
+fn flatten_loop(segments: &[FitSegment], tolerance: f64) -> Vec<Point2D> {
+    let mut points = Vec::new();
+    for segment in segments {
+        match segment {
+            FitSegment::Line(line) => points.push(line.start),
+            FitSegment::Arc(arc) => {
+                let mut chords = flatten(arc, tolerance);
+                chords.pop();
+                points.extend(chords);
+            }
+        }
+    }
+    points
+}
+
+/// A face of the graph, traced with the face on the left
+struct Face {
+    segments: Vec<FitSegment>,
+    area: f64,
+    polygon: Polygon2D,
+    component: usize,
+}
+
+/// Split pieces joined at shared vertices
+struct Graph {
+    points: Vec<Point2D>,
+    /// Pieces running between two distinct vertices
+    edges: Vec<(FitSegment, usize, usize)>,
+}
+
+impl Graph {
+    fn new(pieces: Vec<FitSegment>, tolerance: f64) -> Self {
+        let mut graph = Graph {
+            points: Vec::new(),
+            edges: Vec::new(),
+        };
+        let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+        let mut between: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+
+        for piece in pieces {
+            let from = graph.vertex(piece.start(), tolerance, &mut grid);
+            let to = graph.vertex(piece.end(), tolerance, &mut grid);
+            if from == to {
+                continue;
+            }
+            let piece = match piece {
+                FitSegment::Line(_) => FitSegment::Line(LineSegment2D::new(graph.points[from], graph.points[to])),
+                arc => arc,
+            };
+            // Drop pieces that retrace one already between the same vertices
+            let middle = Self::middle(&piece);
+            let key = (from.min(to), from.max(to));
+            let twins = between.entry(key).or_default();
+            if twins
+                .iter()
+                .any(|&e| Self::middle(&graph.edges[e].0).distance_to(&middle) <= tolerance)
+            {
+                continue;
+            }
+            twins.push(graph.edges.len());
+            graph.edges.push((piece, from, to));
+        }
+        graph.prune();
+        graph
+    }
+
+    /// Index of the vertex within `tolerance` of `point`, adding one if none
+    fn vertex(&mut self, point: Point2D, tolerance: f64, grid: &mut HashMap<(i64, i64), Vec<usize>>) -> usize {
+        let cell = |v: f64| (v / tolerance).floor() as i64;
+        let (cx, cy) = (cell(point.x), cell(point.y));
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if let Some(found) = grid.get(&(cx + dx, cy + dy)).and_then(|candidates| {
+                    candidates
+                        .iter()
+                        .copied()
+                        .find(|&v| self.points[v].distance_to(&point) <= tolerance)
+                }) {
+                    return found;
+                }
+            }
+        }
+        self.points.push(point);
+        grid.entry((cx, cy)).or_default().push(self.points.len() - 1);
+        self.points.len() - 1
+    }
+
+    fn middle(segment: &FitSegment) -> Point2D {
+        match segment {
+            FitSegment::Line(line) => line.midpoint(),
+            FitSegment::Arc(arc) => {
+                let half = arc.sweep_angle() / 2.0;
+                arc.point_at_angle(if arc.ccw {
+                    arc.start_angle + half
+                } else {
+                    arc.start_angle - half
+                })
+            }
+        }
+    }
+
+    /// Remove edges with a free end, repeatedly, as they bound nothing
+    fn prune(&mut self) {
+        let mut degree = vec![0usize; self.points.len()];
+        for &(_, from, to) in &self.edges {
+            degree[from] += 1;
+            degree[to] += 1;
+        }
+        let mut removed = vec![false; self.edges.len()];
+        loop {
+            let mut changed = false;
+            for (e, &(_, from, to)) in self.edges.iter().enumerate() {
+                if !removed[e] && (degree[from] == 1 || degree[to] == 1) {
+                    removed[e] = true;
+                    degree[from] -= 1;
+                    degree[to] -= 1;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        let mut kept = removed.iter().map(|r| !r);
+        self.edges.retain(|_| kept.next().unwrap_or(false));
+    }
+
+    /// Half-edge `h` as a segment in its direction of travel: even
+    /// half-edges run along their edge, odd ones against it
+    fn half_edge(&self, h: usize) -> (FitSegment, usize, usize) {
+        let (segment, from, to) = self.edges[h / 2];
+        if h.is_multiple_of(2) {
+            (segment, from, to)
+        } else {
+            (reverse(&segment), to, from)
+        }
+    }
+
+    fn faces(&self, tolerance: f64) -> Vec<Face> {
+        let half_edges = self.edges.len() * 2;
+
+        // Outgoing half-edges at each vertex, counterclockwise
+        let mut outgoing: Vec<Vec<(usize, f64, f64)>> = vec![Vec::new(); self.points.len()];
+        for h in 0..half_edges {
+            let (segment, from, _) = self.half_edge(h);
+            let (angle, curvature) = departure(&segment);
+            outgoing[from].push((h, angle, curvature));
+        }
+        let mut position = vec![0; half_edges];
+        for around in &mut outgoing {
+            around.sort_by(|a, b| {
+                if (a.1 - b.1).abs() < ANGLE_TOLERANCE {
+                    a.2.total_cmp(&b.2)
+                } else {
+                    a.1.total_cmp(&b.1)
+                }
+            });
+            for (i, &(h, _, _)) in around.iter().enumerate() {
+                position[h] = i;
+            }
+        }
+
+        // Arriving along h, leave by the half-edge just clockwise of the
+        // way back
+        let next = |h: usize| {
+            let (_, _, at) = self.half_edge(h);
+            let around = &outgoing[at];
+            around[(position[h ^ 1] + around.len() - 1) % around.len()].0
+        };
+
+        let component = self.components();
+        let mut visited = vec![false; half_edges];
+        let mut faces = Vec::new();
+        for start in 0..half_edges {
+            if visited[start] {
+                continue;
+            }
+            let mut segments = Vec::new();
+            let mut h = start;
+            while !visited[h] {
+                visited[h] = true;
+                segments.push(self.half_edge(h).0);
+                h = next(h);
+            }
+            let polygon = Polygon2D::new(flatten_loop(&segments, tolerance));
+            faces.push(Face {
+                area: signed_area(&segments),
+                component: component[self.edges[start / 2].1],
+                segments,
+                polygon,
+            });
+        }
+        faces
+    }
+
+    /// Connected component of each vertex
+    fn components(&self) -> Vec<usize> {
+        fn find(parent: &mut [usize], v: usize) -> usize {
+            let mut root = v;
+            while parent[root] != root {
+                root = parent[root];
+            }
+            let mut v = v;
+            while parent[v] != root {
+                let up = parent[v];
+                parent[v] = root;
+                v = up;
+            }
+            root
+        }
+        let mut parent: Vec<usize> = (0..self.points.len()).collect();
+        for &(_, from, to) in &self.edges {
+            let (a, b) = (find(&mut parent, from), find(&mut parent, to));
+            parent[a.max(b)] = a.min(b);
+        }
+        (0..self.points.len()).map(|v| find(&mut parent, v)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::arc::Circle2D;
+
+    fn line(x0: f64, y0: f64, x1: f64, y1: f64) -> LineSegment2D {
+        LineSegment2D::new(Point2D::new(x0, y0), Point2D::new(x1, y1))
+    }
+
+    #[test]
+    fn test_smallest_enclosing_loop() {
+        // A 10 x 10 square split by a vertical line, with lines overshooting
+        // the corners, a spur touching the middle line and a loose stroke
+        let lines = [
+            line(-1.0, 0.0, 11.0, 0.0),
+            line(10.0, -1.0, 10.0, 11.0),
+            line(11.0, 10.0, -1.0, 10.0),
+            line(0.0, 11.0, 0.0, -1.0),
+            line(4.0, 0.0, 4.0, 10.0),
+            line(1.0, 5.0, 4.0, 5.0 + 1e-7),
+            line(2.0, 2.0, 3.0, 3.0),
+        ];
+        let curves: Vec<CurveRef> = lines.iter().map(CurveRef::from).collect();
+        let options = BoundaryOptions::default();
+
+        let right = boundary_at(&curves, Point2D::new(7.0, 5.0), &options).unwrap();
+        assert!((right.area() - 60.0).abs() < 1e-9);
+        assert!(right.islands.is_empty());
+
+        let left = boundary_at(&curves, Point2D::new(2.0, 8.0), &options).unwrap();
+        assert!((left.area() - 40.0).abs() < 1e-9);
+        assert!((left.perimeter() - 28.0).abs() < 1e-6);
+
+        assert!(boundary_at(&curves, Point2D::new(20.0, 5.0), &options).is_none());
+    }
+
+    #[test]
+    fn test_islands_and_arcs() {
+        // A square with a circular island holding a smaller square, and a
+        // rounded notch in the right edge
+        let lines = [
+            line(0.0, 0.0, 20.0, 0.0),
+            line(20.0, 0.0, 20.0, 8.0),
+            line(20.0, 12.0, 20.0, 20.0),
+            line(20.0, 20.0, 0.0, 20.0),
+            line(0.0, 20.0, 0.0, 0.0),
+            line(9.0, 9.0, 11.0, 9.0),
+            line(11.0, 9.0, 11.0, 11.0),
+            line(11.0, 11.0, 9.0, 11.0),
+            line(9.0, 11.0, 9.0, 9.0),
+        ];
+        let notch = Arc2D::new(Point2D::new(20.0, 10.0), 2.0, -PI / 2.0, PI / 2.0, false);
+        let island = Circle2D::new(Point2D::new(10.0, 10.0), 3.0);
+        let mut curves: Vec<CurveRef> = lines.iter().map(CurveRef::from).collect();
+        curves.push((&notch).into());
+        curves.push((&island).into());
+
+        let outer_area = 400.0 - PI * 4.0 / 2.0;
+        let region = boundary_at(&curves, Point2D::new(2.0, 2.0), &BoundaryOptions::default()).unwrap();
+        assert_eq!(region.islands.len(), 1);
+        assert!(signed_area(&region.outer.segments()) > 0.0);
+        assert!(signed_area(&region.islands[0].segments()) < 0.0);
+        assert!((region.area() - (outer_area - 9.0 * PI)).abs() < 1e-9);
+
+        let without = BoundaryOptions {
+            detect_islands: false,
+            ..Default::default()
+        };
+        let region = boundary_at(&curves, Point2D::new(2.0, 2.0), &without).unwrap();
+        assert!((region.area() - outer_area).abs() < 1e-9);
+
+        // Between the circle and the inner square
+        let ring = boundary_at(&curves, Point2D::new(10.0, 12.5), &BoundaryOptions::default()).unwrap();
+        assert!((ring.area() - (9.0 * PI - 4.0)).abs() < 1e-9);
+        let polygon = ring.to_polygon(1e-4);
+        assert_eq!(polygon.holes.len(), 1);
+        assert!((polygon.area() - ring.area()).abs() < 0.01);
+    }
+}
@@ -0,0 +1,908 @@
+//! Clipping against rectangular and polygonal windows
+//!
+//! A [`ClipWindow`] is a closed region bounded by an outer loop and any
+//! number of hole loops, convex or not. Segments, polylines, arcs and
+//! circles are cut where they cross the window boundary and the pieces on
+//! the requested [`ClipSide`] are kept; pieces running along the boundary
+//! count as inside. Pieces that meet again at a vertex or a point where the
+//! curve only touches the boundary are joined, and on closed curves the
+//! pieces either side of the start point are joined too.
+//!
+//! Filled regions are clipped as areas. Both boundaries are split at their
+//! crossings, the region's edges inside (or outside) the window and the
+//! window's edges inside the region are kept, and the kept edges are
+//! chained back into loops. Edges the two boundaries share are kept once
+//! when the region and the window lie on the same side of them, so abutting
+//! areas do not leave slivers. Unlike Sutherland–Hodgman this works for
+//! non-convex windows and leaves no zero-width bridges where a region
+//! leaves the window and comes back: the result is a list of polygons with
+//! holes.
+
+use crate::core::precision::EPSILON;
+use crate::geometry::arc::{Arc2D, Circle2D};
+use crate::geometry::line::{LineSegment2D, Polyline2D};
+use crate::geometry::point::Point2D;
+use crate::geometry::polygon::Polygon2D;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+
+/// Which side of the window boundary to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClipSide {
+    /// Keep what lies inside the window (viewports, crossing selection)
+    Inside,
+    /// Keep what lies outside the window (trimming, wipeouts)
+    Outside,
+}
+
+/// Result of clipping a circle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CircleClip {
+    /// The whole circle is kept
+    Whole,
+    /// The arcs that are kept, if any
+    Arcs(Vec<Arc2D>),
+}
+
+/// Closed clip boundary with optional holes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipWindow {
+    /// Outer loop counter-clockwise, followed by holes clockwise
+    rings: Vec<Vec<Point2D>>,
+    /// Distance below which points count as on the boundary
+    tolerance: f64,
+}
+
+/// Where a point lies relative to a set of loops
+enum Location {
+    Inside,
+    Outside,
+    /// On the boundary, along an edge with the given direction
+    Boundary(Point2D),
+}
+
+impl ClipWindow {
+    /// Axis-aligned rectangular window spanning two corners
+    pub fn rectangle(a: Point2D, b: Point2D) -> Self {
+        let (min, max) = (
+            Point2D::new(a.x.min(b.x), a.y.min(b.y)),
+            Point2D::new(a.x.max(b.x), a.y.max(b.y)),
+        );
+        Self::from_polygon(&Polygon2D::rectangle(min, max))
+    }
+
+    /// Window bounded by a polygon and its holes, in either winding
+    pub fn from_polygon(polygon: &Polygon2D) -> Self {
+        let rings = normalized_rings(polygon);
+        let extent = rings
+            .iter()
+            .flatten()
+            .map(|p| p.x.abs().max(p.y.abs()))
+            .fold(1.0, f64::max);
+        Self {
+            rings,
+            tolerance: EPSILON * extent,
+        }
+    }
+
+    /// Window bounded by a single loop of points
+    pub fn from_points(points: Vec<Point2D>) -> Self {
+        Self::from_polygon(&Polygon2D::new(points))
+    }
+
+    /// Set the distance below which points count as on the boundary
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance.max(0.0);
+        self
+    }
+
+    /// Boundary tolerance
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    /// Whether the window encloses no area
+    pub fn is_empty(&self) -> bool {
+        self.rings.is_empty()
+    }
+
+    /// Whether a point lies inside the window or on its boundary
+    pub fn contains(&self, point: &Point2D) -> bool {
+        !matches!(
+            locate(&self.rings, *point, self.tolerance),
+            Location::Outside
+        )
+    }
+
+    /// Pieces of a segment on the given side of the window
+    pub fn clip_segment(&self, segment: &LineSegment2D, side: ClipSide) -> Vec<LineSegment2D> {
+        let path = Polyline2D::new(vec![segment.start, segment.end], false);
+        self.clip_polyline(&path, side)
+            .into_iter()
+            .map(|piece| {
+                LineSegment2D::new(piece.vertices[0], piece.vertices[piece.vertices.len() - 1])
+            })
+            .collect()
+    }
+
+    /// Pieces of a polyline on the given side of the window
+    ///
+    /// A closed polyline that stays on one side comes back whole and closed;
+    /// otherwise the pieces are open.
+    pub fn clip_polyline(&self, polyline: &Polyline2D, side: ClipSide) -> Vec<Polyline2D> {
+        let vertices = &polyline.vertices;
+        let n = vertices.len();
+        let segments = match n {
+            0 => return Vec::new(),
+            1 => 0,
+            _ if polyline.closed => n,
+            _ => n - 1,
+        };
+        if segments == 0 {
+            return if self.keeps(vertices[0], side) {
+                vec![polyline.clone()]
+            } else {
+                Vec::new()
+            };
+        }
+
+        let vertex = |k: usize| vertices[k % n];
+        let at = |s: f64| {
+            let s = if s > segments as f64 {
+                s - segments as f64
+            } else {
+                s
+            };
+            let i = (s.floor() as usize).min(segments - 1);
+            vertex(i).lerp(&vertex(i + 1), s - i as f64)
+        };
+
+        let mut breaks: Vec<(f64, bool)> = (0..=segments).map(|k| (k as f64, true)).collect();
+        for i in 0..segments {
+            for (a, b) in self.edges() {
+                for (t, _) in segment_params(vertex(i), vertex(i + 1), a, b, self.tolerance) {
+                    breaks.push((i as f64 + t, false));
+                }
+            }
+        }
+
+        let intervals = kept_intervals(
+            breaks,
+            segments as f64,
+            polyline.closed,
+            &at,
+            self.tolerance,
+            |p| self.keeps(p, side),
+        );
+        if polyline.closed && intervals == [(0.0, segments as f64)] {
+            return vec![polyline.clone()];
+        }
+
+        intervals
+            .into_iter()
+            .map(|(s0, s1)| {
+                let mut points = vec![at(s0)];
+                let first = s0.floor() as usize + 1;
+                let last = s1.ceil() as usize;
+                points.extend((first..last).map(vertex));
+                points.push(at(s1));
+                Polyline2D::new(points, false)
+            })
+            .collect()
+    }
+
+    /// Pieces of an arc on the given side of the window
+    pub fn clip_arc(&self, arc: &Arc2D, side: ClipSide) -> Vec<Arc2D> {
+        let sweep = arc.sweep_angle();
+        if sweep <= EPSILON {
+            return Vec::new();
+        }
+        let direction = if arc.ccw { 1.0 } else { -1.0 };
+        self.circular_intervals(
+            arc.center,
+            arc.radius,
+            arc.start_angle,
+            direction * sweep,
+            side,
+        )
+        .into_iter()
+        .map(|(a0, a1)| Arc2D::new(arc.center, arc.radius, a0, a1, arc.ccw))
+        .collect()
+    }
+
+    /// Pieces of a circle on the given side of the window
+    pub fn clip_circle(&self, circle: &Circle2D, side: ClipSide) -> CircleClip {
+        let pieces = self.circular_intervals(circle.center, circle.radius, 0.0, TAU, side);
+        match pieces.as_slice() {
+            [(a0, a1)] if a1 - a0 >= TAU - EPSILON => CircleClip::Whole,
+            _ => CircleClip::Arcs(
+                pieces
+                    .into_iter()
+                    .map(|(a0, a1)| Arc2D::new(circle.center, circle.radius, a0, a1, true))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Area of a filled region on the given side of the window
+    ///
+    /// The result may be several polygons, each with its outer boundary
+    /// counter-clockwise and its holes clockwise.
+    pub fn clip_polygon(&self, polygon: &Polygon2D, side: ClipSide) -> Vec<Polygon2D> {
+        let subject = normalized_rings(polygon);
+        if subject.is_empty() {
+            return Vec::new();
+        }
+        let tol = self.tolerance;
+        let (subject_pieces, window_pieces) = split_boundaries(&subject, &self.rings, tol);
+
+        let mut kept = Vec::new();
+        for (a, b) in subject_pieces {
+            let keep = match (locate(&self.rings, a.midpoint(&b), tol), side) {
+                (Location::Inside, ClipSide::Inside) | (Location::Outside, ClipSide::Outside) => {
+                    true
+                }
+                (Location::Boundary(along), ClipSide::Inside) => along.dot(&(b - a)) > 0.0,
+                (Location::Boundary(along), ClipSide::Outside) => along.dot(&(b - a)) < 0.0,
+                _ => false,
+            };
+            if keep {
+                kept.push((a, b));
+            }
+        }
+        for (a, b) in window_pieces {
+            if matches!(locate(&subject, a.midpoint(&b), tol), Location::Inside) {
+                kept.push(match side {
+                    ClipSide::Inside => (a, b),
+                    ClipSide::Outside => (b, a),
+                });
+            }
+        }
+
+        assemble(chain_loops(&kept, tol), tol)
+    }
+
+    /// Kept stretches of a circular curve from `start` turning through
+    /// `sweep` (negative for clockwise), as pairs of start and end angles
+    fn circular_intervals(
+        &self,
+        center: Point2D,
+        radius: f64,
+        start: f64,
+        sweep: f64,
+        side: ClipSide,
+    ) -> Vec<(f64, f64)> {
+        if radius <= self.tolerance {
+            return if self.keeps(center, side) {
+                vec![(start, start + sweep)]
+            } else {
+                Vec::new()
+            };
+        }
+        let closed = sweep.abs() >= TAU - EPSILON;
+        let angle = |t: f64| start + sweep * t;
+        let at = |t: f64| {
+            let theta = angle(t);
+            Point2D::new(
+                center.x + radius * theta.cos(),
+                center.y + radius * theta.sin(),
+            )
+        };
+
+        let mut breaks = vec![(0.0, true), (1.0, true)];
+        for (a, b) in self.edges() {
+            for theta in circle_crossings(center, radius, a, b, self.tolerance) {
+                let offset = (sweep.signum() * (theta - start)).rem_euclid(TAU);
+                if offset <= sweep.abs() {
+                    breaks.push((offset / sweep.abs(), false));
+                }
+            }
+        }
+
+        kept_intervals(breaks, 1.0, closed, &at, self.tolerance, |p| {
+            self.keeps(p, side)
+        })
+        .into_iter()
+        .map(|(t0, t1)| (angle(t0), angle(t1)))
+        .collect()
+    }
+
+    fn keeps(&self, point: Point2D, side: ClipSide) -> bool {
+        match side {
+            ClipSide::Inside => self.contains(&point),
+            ClipSide::Outside => !self.contains(&point),
+        }
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Point2D, Point2D)> + '_ {
+        ring_edges(&self.rings)
+    }
+}
+
+/// Outer loop counter-clockwise then holes clockwise, without repeated
+/// closing points or loops too small to enclose anything
+fn normalized_rings(polygon: &Polygon2D) -> Vec<Vec<Point2D>> {
+    let clean = |ring: &[Point2D], ccw: bool| {
+        let mut ring: Vec<Point2D> = ring.to_vec();
+        ring.dedup_by(|a, b| a.approx_eq(b));
+        while ring.len() > 1 && ring[0].approx_eq(&ring[ring.len() - 1]) {
+            ring.pop();
+        }
+        let area = signed_area(&ring);
+        if ring.len() < 3 || area.abs() < EPSILON {
+            return None;
+        }
+        if (area > 0.0) != ccw {
+            ring.reverse();
+        }
+        Some(ring)
+    };
+
+    let Some(outer) = clean(&polygon.vertices, true) else {
+        return Vec::new();
+    };
+    std::iter::once(outer)
+        .chain(polygon.holes.iter().filter_map(|hole| clean(hole, false)))
+        .collect()
+}
+
+fn ring_edges(rings: &[Vec<Point2D>]) -> impl Iterator<Item = (Point2D, Point2D)> + '_ {
+    rings
+        .iter()
+        .flat_map(|ring| (0..ring.len()).map(move |i| (ring[i], ring[(i + 1) % ring.len()])))
+}
+
+fn signed_area(ring: &[Point2D]) -> f64 {
+    (0..ring.len())
+        .map(|i| ring[i].cross(&ring[(i + 1) % ring.len()]))
+        .sum::<f64>()
+        / 2.0
+}
+
+fn point_segment_distance(p: Point2D, a: Point2D, b: Point2D) -> f64 {
+    let d = b - a;
+    let len2 = d.dot(&d);
+    let t = if len2 > 0.0 {
+        ((p - a).dot(&d) / len2).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    p.distance_to(&a.lerp(&b, t))
+}
+
+/// Locate a point against loops by the even-odd rule
+fn locate(rings: &[Vec<Point2D>], p: Point2D, tol: f64) -> Location {
+    let mut inside = false;
+    for (a, b) in ring_edges(rings) {
+        if point_segment_distance(p, a, b) <= tol {
+            return Location::Boundary(b - a);
+        }
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y) {
+            inside = !inside;
+        }
+    }
+    if inside {
+        Location::Inside
+    } else {
+        Location::Outside
+    }
+}
+
+/// Parameters `(t, u)` where segment `a0 a1` meets segment `b0 b1`; both
+/// ends of the shared stretch for collinear overlaps
+fn segment_params(a0: Point2D, a1: Point2D, b0: Point2D, b1: Point2D, tol: f64) -> Vec<(f64, f64)> {
+    let r = a1 - a0;
+    let s = b1 - b0;
+    let (len_r, len_s) = (r.dot(&r).sqrt(), s.dot(&s).sqrt());
+    if len_r <= tol || len_s <= tol {
+        return Vec::new();
+    }
+    let (eps_t, eps_u) = (tol / len_r, tol / len_s);
+    let q = b0 - a0;
+    let denom = r.cross(&s);
+
+    if denom.abs() > EPSILON * len_r * len_s {
+        let t = q.cross(&s) / denom;
+        let u = q.cross(&r) / denom;
+        let on = |v: f64, eps: f64| (-eps..=1.0 + eps).contains(&v);
+        if on(t, eps_t) && on(u, eps_u) {
+            return vec![(t.clamp(0.0, 1.0), u.clamp(0.0, 1.0))];
+        }
+        // Nearly parallel segments can still touch end to side
+    }
+
+    let mut params = Vec::new();
+    let mut touch = |t: f64, u: f64| {
+        if (-eps_t..=1.0 + eps_t).contains(&t) && (-eps_u..=1.0 + eps_u).contains(&u) {
+            let p = a0.lerp(&a1, t.clamp(0.0, 1.0));
+            let q = b0.lerp(&b1, u.clamp(0.0, 1.0));
+            if p.distance_to(&q) <= tol {
+                params.push((t.clamp(0.0, 1.0), u.clamp(0.0, 1.0)));
+            }
+        }
+    };
+    let project = |p: Point2D, o: Point2D, d: Point2D| (p - o).dot(&d) / d.dot(&d);
+    touch(project(b0, a0, r), 0.0);
+    touch(project(b1, a0, r), 1.0);
+    touch(0.0, project(a0, b0, s));
+    touch(1.0, project(a1, b0, s));
+    params
+}
+
+/// Angles at which a circle meets a segment
+fn circle_crossings(center: Point2D, radius: f64, a: Point2D, b: Point2D, tol: f64) -> Vec<f64> {
+    let d = b - a;
+    let f = a - center;
+    let qa = d.dot(&d);
+    if qa <= tol * tol {
+        return Vec::new();
+    }
+    let qb = 2.0 * f.dot(&d);
+    let qc = f.dot(&f) - radius * radius;
+    let disc = qb * qb - 4.0 * qa * qc;
+    // Treat a segment passing within tolerance of the circle as tangent
+    let slack = 4.0 * qa * (2.0 * radius * tol);
+    if disc < -slack {
+        return Vec::new();
+    }
+    let root = disc.max(0.0).sqrt();
+    let eps = tol / qa.sqrt();
+    let mut ts = vec![(-qb - root) / (2.0 * qa)];
+    if root > 0.0 {
+        ts.push((-qb + root) / (2.0 * qa));
+    }
+    ts.into_iter()
+        .filter(|t| (-eps..=1.0 + eps).contains(t))
+        .map(|t| {
+            let p = a.lerp(&b, t.clamp(0.0, 1.0)) - center;
+            p.y.atan2(p.x)
+        })
+        .collect()
+}
+
+/// Merge parameter intervals between breaks whose midpoints pass `keep`
+///
+/// `breaks` are parameters in `[0, end]`, flagged when they are vertices to
+/// prefer over nearby crossings. On closed curves a kept interval running
+/// into `end` joins the one starting at 0, giving an interval past `end`.
+fn kept_intervals(
+    mut breaks: Vec<(f64, bool)>,
+    end: f64,
+    closed: bool,
+    at: &dyn Fn(f64) -> Point2D,
+    tol: f64,
+    keep: impl Fn(Point2D) -> bool,
+) -> Vec<(f64, f64)> {
+    breaks.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut params: Vec<(f64, bool)> = Vec::with_capacity(breaks.len());
+    for (t, fixed) in breaks {
+        match params.last_mut() {
+            // Vertices never merge with each other: a closed curve ends where it starts
+            Some(last) if !(fixed && last.1) && at(last.0).distance_to(&at(t)) <= tol => {
+                if fixed && !last.1 {
+                    *last = (t, fixed);
+                }
+            }
+            _ => params.push((t, fixed)),
+        }
+    }
+    // The end must stay put even when a crossing sits right on it
+    if let Some(last) = params.last_mut() {
+        last.0 = end;
+    }
+
+    let mut intervals: Vec<(f64, f64)> = Vec::new();
+    for pair in params.windows(2) {
+        let (t0, t1) = (pair[0].0, pair[1].0);
+        if !keep(at((t0 + t1) / 2.0)) {
+            continue;
+        }
+        match intervals.last_mut() {
+            Some(last) if last.1 == t0 => last.1 = t1,
+            _ => intervals.push((t0, t1)),
+        }
+    }
+
+    // Zero-length stretches between repeated vertices are not pieces
+    intervals.retain(|&(t0, t1)| {
+        let mid = at((t0 + t1) / 2.0);
+        t1 - t0 >= end || at(t0).distance_to(&mid) > tol || at(t1).distance_to(&mid) > tol
+    });
+
+    if closed
+        && intervals.len() > 1
+        && intervals[0].0 == 0.0
+        && intervals[intervals.len() - 1].1 == end
+    {
+        let first = intervals.remove(0);
+        if let Some(last) = intervals.last_mut() {
+            last.1 = end + first.1;
+        }
+    }
+    intervals
+}
+
+/// Split two sets of loops at every point where they meet, sharing the
+/// split points so the pieces chain exactly
+#[allow(clippy::type_complexity)]
+fn split_boundaries(
+    a: &[Vec<Point2D>],
+    b: &[Vec<Point2D>],
+    tol: f64,
+) -> (Vec<(Point2D, Point2D)>, Vec<(Point2D, Point2D)>) {
+    let edges_a: Vec<(Point2D, Point2D)> = ring_edges(a).collect();
+    let edges_b: Vec<(Point2D, Point2D)> = ring_edges(b).collect();
+    let mut splits_a: Vec<Vec<(f64, Point2D)>> = vec![Vec::new(); edges_a.len()];
+    let mut splits_b: Vec<Vec<(f64, Point2D)>> = vec![Vec::new(); edges_b.len()];
+
+    let bounds = |(p, q): &(Point2D, Point2D)| {
+        (
+            p.x.min(q.x) - tol,
+            p.y.min(q.y) - tol,
+            p.x.max(q.x) + tol,
+            p.y.max(q.y) + tol,
+        )
+    };
+    let boxes_b: Vec<_> = edges_b.iter().map(bounds).collect();
+
+    for (i, ea) in edges_a.iter().enumerate() {
+        let ba = bounds(ea);
+        for (j, eb) in edges_b.iter().enumerate() {
+            let bb = boxes_b[j];
+            if ba.0 > bb.2 || bb.0 > ba.2 || ba.1 > bb.3 || bb.1 > ba.3 {
+                continue;
+            }
+            for (t, u) in segment_params(ea.0, ea.1, eb.0, eb.1, tol) {
+                let p = if ea.0.distance_to(&ea.0.lerp(&ea.1, t)) <= tol {
+                    ea.0
+                } else if ea.1.distance_to(&ea.0.lerp(&ea.1, t)) <= tol {
+                    ea.1
+                } else if eb.0.distance_to(&eb.0.lerp(&eb.1, u)) <= tol {
+                    eb.0
+                } else if eb.1.distance_to(&eb.0.lerp(&eb.1, u)) <= tol {
+                    eb.1
+                } else {
+                    ea.0.lerp(&ea.1, t)
+                };
+                splits_a[i].push((t, p));
+                splits_b[j].push((u, p));
+            }
+        }
+    }
+
+    let pieces = |edges: &[(Point2D, Point2D)], splits: Vec<Vec<(f64, Point2D)>>| {
+        let mut out = Vec::new();
+        for (&(start, end), mut cuts) in edges.iter().zip(splits) {
+            cuts.sort_by(|x, y| x.0.total_cmp(&y.0));
+            let mut points = vec![start];
+            for (_, p) in cuts.into_iter().chain(std::iter::once((1.0, end))) {
+                if p.distance_to(&points[points.len() - 1]) > tol {
+                    points.push(p);
+                } else if p == end {
+                    let last = points.len() - 1;
+                    if last > 0 {
+                        points[last] = end;
+                    }
+                }
+            }
+            out.extend(points.windows(2).map(|w| (w[0], w[1])));
+        }
+        out
+    };
+    (pieces(&edges_a, splits_a), pieces(&edges_b, splits_b))
+}
+
+/// Chain directed edges into closed loops, taking the sharpest left turn
+/// where several edges leave the same point so touching loops stay apart
+fn chain_loops(edges: &[(Point2D, Point2D)], tol: f64) -> Vec<Vec<Point2D>> {
+    let cell = tol.max(EPSILON) * 4.0;
+    let mut nodes: Vec<Point2D> = Vec::new();
+    let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    let mut node_of = |p: Point2D| -> usize {
+        let (cx, cy) = ((p.x / cell).floor() as i64, (p.y / cell).floor() as i64);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if let Some(ids) = grid.get(&(cx + dx, cy + dy)) {
+                    if let Some(&id) = ids.iter().find(|&&id| nodes[id].distance_to(&p) <= tol) {
+                        return id;
+                    }
+                }
+            }
+        }
+        nodes.push(p);
+        grid.entry((cx, cy)).or_default().push(nodes.len() - 1);
+        nodes.len() - 1
+    };
+
+    let links: Vec<(usize, usize)> = edges
+        .iter()
+        .map(|&(a, b)| (node_of(a), node_of(b)))
+        .collect();
+    let mut outgoing: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (e, &(from, to)) in links.iter().enumerate() {
+        if from != to {
+            outgoing.entry(from).or_default().push(e);
+        }
+    }
+
+    let mut used = vec![false; links.len()];
+    let mut loops = Vec::new();
+    for start in 0..links.len() {
+        if used[start] || links[start].0 == links[start].1 {
+            continue;
+        }
+        used[start] = true;
+        let origin = links[start].0;
+        let mut ring = vec![nodes[origin]];
+        let mut current = start;
+        let closed = loop {
+            let (from, to) = links[current];
+            if to == origin {
+                break true;
+            }
+            ring.push(nodes[to]);
+            let incoming = nodes[to] - nodes[from];
+            let next = outgoing.get(&to).and_then(|candidates| {
+                candidates
+                    .iter()
+                    .copied()
+                    .filter(|&e| !used[e])
+                    .max_by(|&x, &y| {
+                        let turn = |e: usize| {
+                            let out = nodes[links[e].1] - nodes[links[e].0];
+                            incoming.cross(&out).atan2(incoming.dot(&out))
+                        };
+                        turn(x).total_cmp(&turn(y))
+                    })
+            });
+            match next {
+                Some(e) => {
+                    used[e] = true;
+                    current = e;
+                }
+                None => break false,
+            }
+        };
+        if closed {
+            loops.push(ring);
+        }
+    }
+    loops
+}
+
+/// Drop collinear vertices and degenerate loops, then sort loops into
+/// outer boundaries and the holes they contain
+fn assemble(loops: Vec<Vec<Point2D>>, tol: f64) -> Vec<Polygon2D> {
+    let mut outers: Vec<(Vec<Point2D>, f64)> = Vec::new();
+    let mut holes: Vec<Vec<Point2D>> = Vec::new();
+
+    for mut ring in loops {
+        let mut i = 0;
+        while ring.len() >= 3 && i < ring.len() {
+            let n = ring.len();
+            let (prev, p, next) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+            if point_segment_distance(p, prev, next) <= tol {
+                ring.remove(i);
+                i = i.saturating_sub(1);
+            } else {
+                i += 1;
+            }
+        }
+        let area = signed_area(&ring);
+        if ring.len() < 3 || area.abs() <= tol * tol {
+            continue;
+        }
+        if area > 0.0 {
+            outers.push((ring, area));
+        } else {
+            holes.push(ring);
+        }
+    }
+
+    let mut polygons: Vec<Polygon2D> = outers
+        .iter()
+        .map(|(ring, _)| Polygon2D::new(ring.clone()))
+        .collect();
+    for hole in holes {
+        // A point just to the left of a hole edge lies in the kept area
+        let (a, b) = (hole[0], hole[1]);
+        let d = (b - a).normalize();
+        let probe = a.midpoint(&b) + Point2D::new(-d.y, d.x) * (tol * 16.0).max(EPSILON);
+        let owner = outers
+            .iter()
+            .enumerate()
+            .filter(|(_, (ring, _))| {
+                matches!(
+                    locate(std::slice::from_ref(ring), probe, 0.0),
+                    Location::Inside
+                )
+            })
+            .min_by(|x, y| x.1 .1.total_cmp(&y.1 .1))
+            .map(|(i, _)| i);
+        if let Some(i) = owner {
+            polygons[i].holes.push(hole);
+        }
+    }
+    polygons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn pt(x: f64, y: f64) -> Point2D {
+        Point2D::new(x, y)
+    }
+
+    /// Non-convex "U" window: a 3x3 square with a notch cut from the top
+    fn u_window() -> ClipWindow {
+        ClipWindow::from_points(vec![
+            pt(0.0, 0.0),
+            pt(3.0, 0.0),
+            pt(3.0, 3.0),
+            pt(2.0, 3.0),
+            pt(2.0, 1.0),
+            pt(1.0, 1.0),
+            pt(1.0, 3.0),
+            pt(0.0, 3.0),
+        ])
+    }
+
+    #[test]
+    fn test_clip_segments_and_polylines() {
+        let window = ClipWindow::rectangle(pt(10.0, 10.0), pt(0.0, 0.0));
+        let inside = window.clip_segment(
+            &LineSegment2D::new(pt(-5.0, 5.0), pt(15.0, 5.0)),
+            ClipSide::Inside,
+        );
+        assert_eq!(inside.len(), 1);
+        assert!(
+            inside[0].start.approx_eq(&pt(0.0, 5.0)) && inside[0].end.approx_eq(&pt(10.0, 5.0))
+        );
+        let outside = window.clip_segment(
+            &LineSegment2D::new(pt(-5.0, 5.0), pt(15.0, 5.0)),
+            ClipSide::Outside,
+        );
+        assert_eq!(outside.len(), 2);
+
+        // Across the notch of a non-convex window the line splits in two
+        let pieces = u_window().clip_segment(
+            &LineSegment2D::new(pt(-1.0, 2.0), pt(4.0, 2.0)),
+            ClipSide::Inside,
+        );
+        assert_eq!(pieces.len(), 2);
+        assert!((pieces.iter().map(|s| s.length()).sum::<f64>() - 2.0).abs() < 1e-9);
+
+        // Running along the boundary counts as inside
+        let edge = window.clip_segment(
+            &LineSegment2D::new(pt(-2.0, 0.0), pt(4.0, 0.0)),
+            ClipSide::Inside,
+        );
+        assert_eq!(edge.len(), 1);
+        assert!((edge[0].length() - 4.0).abs() < 1e-9);
+
+        // A closed square straddling the window's corner: one open piece, vertices kept
+        let square = Polyline2D::new(
+            vec![pt(8.0, 8.0), pt(12.0, 8.0), pt(12.0, 12.0), pt(8.0, 12.0)],
+            true,
+        );
+        let clipped = window.clip_polyline(&square, ClipSide::Inside);
+        assert_eq!(clipped.len(), 1);
+        assert_eq!(clipped[0].vertices.len(), 3);
+        assert!(!clipped[0].closed);
+        assert!(clipped[0].vertices[1].approx_eq(&pt(8.0, 8.0)));
+        assert!((clipped[0].length() - 4.0).abs() < 1e-9);
+
+        let small = Polyline2D::new(vec![pt(1.0, 1.0), pt(2.0, 1.0), pt(2.0, 2.0)], true);
+        assert_eq!(
+            window.clip_polyline(&small, ClipSide::Inside),
+            vec![small.clone()]
+        );
+        assert!(window.clip_polyline(&small, ClipSide::Outside).is_empty());
+    }
+
+    #[test]
+    fn test_clip_arcs_and_circles() {
+        let window = ClipWindow::rectangle(pt(0.0, -10.0), pt(10.0, 10.0));
+        let circle = Circle2D::new(pt(0.0, 0.0), 5.0);
+
+        let arcs = |clip: CircleClip| match clip {
+            CircleClip::Arcs(arcs) => arcs,
+            CircleClip::Whole => panic!("circle should be cut"),
+        };
+        let right = arcs(window.clip_circle(&circle, ClipSide::Inside));
+        assert_eq!(right.len(), 1);
+        assert!((right[0].sweep_angle() - PI).abs() < 1e-9);
+        assert!(right[0].midpoint().approx_eq(&pt(5.0, 0.0)));
+
+        // The left half wraps past the circle's start angle and comes back whole
+        let left = arcs(window.clip_circle(&circle, ClipSide::Outside));
+        assert_eq!(left.len(), 1);
+        assert!((left[0].sweep_angle() - PI).abs() < 1e-9);
+        assert!(left[0].midpoint().approx_eq(&pt(-5.0, 0.0)));
+
+        // Clockwise arc across the notch of the U window
+        let arc = Arc2D::new(pt(1.5, 0.0), 2.0, PI, 0.0, false);
+        let pieces = u_window().clip_arc(&arc, ClipSide::Inside);
+        assert_eq!(pieces.len(), 2);
+        for piece in &pieces {
+            assert!(!piece.ccw);
+            let middle = piece.point_at_angle((piece.start_angle + piece.end_angle) / 2.0);
+            assert!(u_window().contains(&middle));
+        }
+        // Inside between x = 0 and 1 and between x = 2 and 3
+        let total: f64 = pieces.iter().map(|a| a.length()).sum();
+        assert!((total - 4.0 * (0.25f64.acos() - 0.75f64.acos())).abs() < 1e-9);
+
+        let inner = Circle2D::new(pt(5.0, 0.0), 1.0);
+        assert_eq!(
+            window.clip_circle(&inner, ClipSide::Inside),
+            CircleClip::Whole
+        );
+        assert_eq!(
+            window.clip_circle(&inner, ClipSide::Outside),
+            CircleClip::Arcs(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_clip_regions() {
+        let window = ClipWindow::rectangle(pt(0.0, 0.0), pt(10.0, 10.0));
+
+        // Overlapping squares
+        let square = Polygon2D::rectangle(pt(5.0, 5.0), pt(15.0, 15.0));
+        let inside = window.clip_polygon(&square, ClipSide::Inside);
+        assert_eq!(inside.len(), 1);
+        assert!((inside[0].area() - 25.0).abs() < 1e-9);
+        assert_eq!(inside[0].vertices.len(), 4);
+        let outside = window.clip_polygon(&square, ClipSide::Outside);
+        assert_eq!(outside.len(), 1);
+        assert!((outside[0].area() - 75.0).abs() < 1e-9);
+
+        // A bar across the U window's notch splits in two, no bridge left behind
+        let bar = Polygon2D::rectangle(pt(-1.0, 2.0), pt(4.0, 2.5));
+        let pieces = u_window().clip_polygon(&bar, ClipSide::Inside);
+        assert_eq!(pieces.len(), 2);
+        assert!((pieces.iter().map(|p| p.area()).sum::<f64>() - 1.0).abs() < 1e-9);
+
+        // A window inside the region becomes a hole when clipped away
+        let big = Polygon2D::rectangle(pt(-5.0, -5.0), pt(20.0, 20.0));
+        let punched = window.clip_polygon(&big, ClipSide::Outside);
+        assert_eq!(punched.len(), 1);
+        assert_eq!(punched[0].holes.len(), 1);
+        assert!((punched[0].area() - (625.0 - 100.0)).abs() < 1e-9);
+        assert!(Polygon2D::new(punched[0].holes[0].clone()).signed_area() < 0.0);
+
+        // Region with a hole straddling the window edge, sharing its bottom edge
+        let framed = Polygon2D::with_holes(
+            vec![pt(5.0, 0.0), pt(15.0, 0.0), pt(15.0, 10.0), pt(5.0, 10.0)],
+            vec![vec![
+                pt(8.0, 4.0),
+                pt(12.0, 4.0),
+                pt(12.0, 6.0),
+                pt(8.0, 6.0),
+            ]],
+        );
+        let clipped = window.clip_polygon(&framed, ClipSide::Inside);
+        assert_eq!(clipped.len(), 1);
+        assert!((clipped[0].area() - (50.0 - 4.0)).abs() < 1e-9);
+
+        // Abutting regions share only an edge
+        let neighbour = Polygon2D::rectangle(pt(10.0, 0.0), pt(20.0, 10.0));
+        assert!(window.clip_polygon(&neighbour, ClipSide::Inside).is_empty());
+        let same = window.clip_polygon(
+            &Polygon2D::rectangle(pt(0.0, 0.0), pt(10.0, 10.0)),
+            ClipSide::Inside,
+        );
+        assert_eq!(same.len(), 1);
+        assert!((same[0].area() - 100.0).abs() < 1e-9);
+        assert!(window
+            .clip_polygon(
+                &Polygon2D::rectangle(pt(0.0, 0.0), pt(10.0, 10.0)),
+                ClipSide::Outside
+            )
+            .is_empty());
+    }
+}
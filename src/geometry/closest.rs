@@ -0,0 +1,773 @@
+//! Closest points and distances
+//!
+//! [`ClosestPoint`] finds the point of a 2D entity closest to a query point,
+//! with its parameter on the entity and the squared distance, and
+//! [`closest_between`] finds the closest pair of points on two entities.
+//! They are the one implementation for snapping to the nearest point,
+//! measuring distances for dimensions and constraints, and locating picks
+//! for trimming.
+//!
+//! Points, lines, segments, polylines, polygons, arcs and circles are
+//! projected onto in closed form. Ellipses, parabolas, hyperbolas, Bezier
+//! curves and splines are sampled on the same chords
+//! [`intersect`](crate::geometry::intersect()) brackets roots on, and every
+//! sign change of the tangential component of the offset to the query point
+//! is refined with the Illinois method; the closest of those roots, the
+//! curve's ends and the samples themselves wins.
+//!
+//! Between two entities the distance from the first entity's point at each
+//! of its samples to the second entity is minimised by golden-section search
+//! around each sampled local minimum. Crossing entities come out at distance
+//! zero to within about 1e-12 of the first entity's parameter range.
+//!
+//! Parameters follow [`CurveRef`]'s conventions for the curves it covers.
+//! Polylines, polygons and arc polylines use the segment index plus the
+//! fraction along that segment, polygons counting the edges of their holes
+//! after those of the outer boundary. Infinite lines use the distance from
+//! their point along their unit direction, and points use zero.
+
+use crate::geometry::arc::{Arc2D, Circle2D, Ellipse2D, EllipticalArc2D};
+use crate::geometry::conic::{Hyperbola2D, Parabola2D};
+use crate::geometry::curve::{BSpline, BezierCurve, NurbsCurve};
+use crate::geometry::fillet::sweep_to;
+use crate::geometry::fitting::{ArcPolyline, FitSegment};
+use crate::geometry::intersect::CurveRef;
+use crate::geometry::line::{Line2D, LineSegment2D, Polyline2D};
+use crate::geometry::point::Point2D;
+use crate::geometry::polygon::Polygon2D;
+use serde::{Deserialize, Serialize};
+
+/// Samples per straight segment when looking for the closest pair
+const SEGMENT_SAMPLES: usize = 8;
+
+/// Samples across a Bezier curve
+const BEZIER_SAMPLES: usize = 32;
+
+/// Root refinement and golden-section iterations
+const MAX_ITERATIONS: usize = 100;
+
+/// Golden-section searches stop at this fraction of the parameter range
+const PARAM_TOLERANCE: f64 = 1e-12;
+
+/// The closest point of an entity to something
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Closest {
+    /// The point on the entity
+    pub point: Point2D,
+    /// Its parameter on the entity
+    pub parameter: f64,
+    /// Squared distance to the query
+    pub distance_squared: f64,
+}
+
+impl Closest {
+    /// Distance to the query
+    pub fn distance(&self) -> f64 {
+        self.distance_squared.sqrt()
+    }
+}
+
+/// The closest points of two entities
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ClosestPair {
+    /// Closest point on the first entity
+    pub first: Closest,
+    /// Closest point on the second entity
+    pub second: Closest,
+}
+
+impl ClosestPair {
+    /// Squared distance between the entities
+    pub fn distance_squared(&self) -> f64 {
+        self.first.distance_squared
+    }
+
+    /// Distance between the entities
+    pub fn distance(&self) -> f64 {
+        self.first.distance()
+    }
+
+    /// The same pair with the entities swapped
+    pub fn swapped(self) -> Self {
+        Self {
+            first: self.second,
+            second: self.first,
+        }
+    }
+}
+
+/// Closest point and distance queries on a 2D entity
+pub trait ClosestPoint {
+    /// Point at parameter `t`
+    fn point_at_parameter(&self, t: f64) -> Point2D;
+
+    /// The entity's closest point to `point`, or `None` if it has no points
+    fn closest_to_point(&self, point: &Point2D) -> Option<Closest>;
+
+    /// Increasing parameters fine enough that the distance from the entity
+    /// to another has at most one local minimum between neighbours
+    ///
+    /// Empty for unbounded entities.
+    fn sample_parameters(&self) -> Vec<f64>;
+
+    /// Squared distance to `point`, or `None` if the entity has no points
+    fn distance_squared_to_point(&self, point: &Point2D) -> Option<f64> {
+        self.closest_to_point(point).map(|closest| closest.distance_squared)
+    }
+
+    /// Closest points of this entity and `other`
+    fn closest_to_entity(&self, other: &dyn ClosestPoint) -> Option<ClosestPair>
+    where
+        Self: Sized,
+    {
+        closest_between(self, other)
+    }
+}
+
+/// Closest points of two entities, or `None` if either has no points
+///
+/// Only local minima of the distance between neighbouring samples of `a`
+/// are found, so two stretches approaching each other closer than the
+/// sample spacing of `a` count as one. Parallel infinite lines are reported
+/// at the point of `a` at parameter zero.
+pub fn closest_between(a: &dyn ClosestPoint, b: &dyn ClosestPoint) -> Option<ClosestPair> {
+    let samples = a.sample_parameters();
+    if samples.is_empty() {
+        return if b.sample_parameters().is_empty() {
+            closest_unbounded(a, b)
+        } else {
+            closest_between(b, a).map(ClosestPair::swapped)
+        };
+    }
+
+    let pair_at = |t: f64| -> Option<ClosestPair> {
+        let point = a.point_at_parameter(t);
+        let second = b.closest_to_point(&point)?;
+        Some(ClosestPair {
+            first: Closest {
+                point,
+                parameter: t,
+                distance_squared: second.distance_squared,
+            },
+            second,
+        })
+    };
+
+    let pairs = samples.iter().map(|&t| pair_at(t)).collect::<Option<Vec<_>>>()?;
+    let tolerance = (samples[samples.len() - 1] - samples[0]).abs().max(1.0) * PARAM_TOLERANCE;
+    let mut best: Option<ClosestPair> = None;
+    for i in 0..pairs.len() {
+        let here = pairs[i].distance_squared();
+        let before = i.checked_sub(1).map(|j| pairs[j].distance_squared());
+        let after = pairs.get(i + 1).map(ClosestPair::distance_squared);
+        if before.is_some_and(|d| d < here) || after.is_some_and(|d| d < here) {
+            continue;
+        }
+        let lo = samples[i.saturating_sub(1)];
+        let hi = samples[(i + 1).min(samples.len() - 1)];
+        let refined = golden_section(lo, hi, tolerance, |t| {
+            pair_at(t).map_or(f64::INFINITY, |pair| pair.distance_squared())
+        });
+        let candidate = pair_at(refined)
+            .filter(|pair| pair.distance_squared() < here)
+            .unwrap_or(pairs[i]);
+        if best.is_none_or(|b| candidate.distance_squared() < b.distance_squared()) {
+            best = Some(candidate);
+        }
+    }
+    best
+}
+
+/// Closest points of two unbounded entities: two infinite lines
+///
+/// The distance from `b` grows linearly either side of where `a` crosses
+/// it, so the crossing is found from the distances at two points of `a`.
+fn closest_unbounded(a: &dyn ClosestPoint, b: &dyn ClosestPoint) -> Option<ClosestPair> {
+    let distance_at = |t: f64| b.distance_squared_to_point(&a.point_at_parameter(t)).map(f64::sqrt);
+    let (d0, d1) = (distance_at(0.0)?, distance_at(1.0)?);
+    [0.0, d0 / (d0 - d1), d0 / (d0 + d1)]
+        .into_iter()
+        .filter(|t| t.is_finite())
+        .filter_map(|t| {
+            let point = a.point_at_parameter(t);
+            let second = b.closest_to_point(&point)?;
+            Some(ClosestPair {
+                first: Closest {
+                    point,
+                    parameter: t,
+                    distance_squared: second.distance_squared,
+                },
+                second,
+            })
+        })
+        .min_by(|p, q| p.distance_squared().total_cmp(&q.distance_squared()))
+}
+
+/// Minimiser of `f` on `[lo, hi]`, assuming it has one there
+fn golden_section<F: Fn(f64) -> f64>(mut lo: f64, mut hi: f64, tolerance: f64, f: F) -> f64 {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let mut x1 = hi - ratio * (hi - lo);
+    let mut x2 = lo + ratio * (hi - lo);
+    let (mut f1, mut f2) = (f(x1), f(x2));
+    for _ in 0..MAX_ITERATIONS {
+        if hi - lo <= tolerance {
+            break;
+        }
+        if f1 <= f2 {
+            hi = x2;
+            x2 = x1;
+            f2 = f1;
+            x1 = hi - ratio * (hi - lo);
+            f1 = f(x1);
+        } else {
+            lo = x1;
+            x1 = x2;
+            f1 = f2;
+            x2 = lo + ratio * (hi - lo);
+            f2 = f(x2);
+        }
+    }
+    if f1 <= f2 {
+        x1
+    } else {
+        x2
+    }
+}
+
+/// Closest point of a smooth curve to `point`, from samples of its
+/// parameter and the roots of the tangential offset between them
+fn project<P, D>(position: P, derivative: D, samples: &[f64], point: &Point2D) -> Option<Closest>
+where
+    P: Fn(f64) -> Point2D,
+    D: Fn(f64) -> Point2D,
+{
+    let closest = |t: f64| {
+        let on_curve = position(t);
+        Closest {
+            point: on_curve,
+            parameter: t,
+            distance_squared: on_curve.distance_squared_to(point),
+        }
+    };
+    // Negative while moving along the curve brings it closer to the point
+    let slope = |t: f64| (position(t) - *point).dot(&derivative(t));
+    let nearer = |a: Closest, b: Closest| if b.distance_squared < a.distance_squared { b } else { a };
+
+    let mut best = samples.iter().map(|&t| closest(t)).reduce(nearer)?;
+    let tolerance = (samples[samples.len() - 1] - samples[0]).abs().max(1.0) * f64::EPSILON;
+    for pair in samples.windows(2) {
+        let (mut a, mut b) = (pair[0], pair[1]);
+        let (mut slope_a, mut slope_b) = (slope(a), slope(b));
+        if !(slope_a < 0.0 && slope_b >= 0.0) {
+            continue;
+        }
+        let mut t = b;
+        let mut side = 0;
+        for _ in 0..MAX_ITERATIONS {
+            t = (a * slope_b - b * slope_a) / (slope_b - slope_a);
+            if !(t > a && t < b) {
+                t = (a + b) / 2.0;
+            }
+            let s = slope(t);
+            if s == 0.0 {
+                break;
+            }
+            // Illinois: halve the stale end's slope when the same end moves twice
+            if s < 0.0 {
+                if side < 0 {
+                    slope_b /= 2.0;
+                }
+                a = t;
+                slope_a = s;
+                side = -1;
+            } else {
+                if side > 0 {
+                    slope_a /= 2.0;
+                }
+                b = t;
+                slope_b = s;
+                side = 1;
+            }
+            if b - a <= tolerance * 4.0 {
+                break;
+            }
+        }
+        best = nearer(best, closest(t));
+    }
+    Some(best)
+}
+
+/// `count + 1` even steps across `[lo, hi]`
+fn even(lo: f64, hi: f64, count: usize) -> Vec<f64> {
+    (0..=count).map(|i| lo + (hi - lo) * i as f64 / count as f64).collect()
+}
+
+fn segment_closest(line: &LineSegment2D, point: &Point2D) -> Closest {
+    let d = line.end - line.start;
+    let length_squared = d.dot(&d);
+    let t = if length_squared > 0.0 {
+        ((*point - line.start).dot(&d) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let on_line = line.start + d * t;
+    Closest {
+        point: on_line,
+        parameter: t,
+        distance_squared: on_line.distance_squared_to(point),
+    }
+}
+
+fn arc_closest(arc: &Arc2D, point: &Point2D) -> Closest {
+    let sweep = arc.sweep_angle();
+    let swept = sweep_to(arc, arc.center.angle_to(point));
+    let t = if swept <= sweep {
+        swept
+    } else if arc.start_point().distance_squared_to(point) <= arc.end_point().distance_squared_to(point) {
+        0.0
+    } else {
+        sweep
+    };
+    let on_arc = CurveRef::Arc(arc).point_at(t);
+    Closest {
+        point: on_arc,
+        parameter: t,
+        distance_squared: on_arc.distance_squared_to(point),
+    }
+}
+
+impl ClosestPoint for CurveRef<'_> {
+    fn point_at_parameter(&self, t: f64) -> Point2D {
+        self.point_at(t)
+    }
+
+    fn closest_to_point(&self, point: &Point2D) -> Option<Closest> {
+        match self {
+            CurveRef::Segment(line) => Some(segment_closest(line, point)),
+            CurveRef::Arc(arc) => Some(arc_closest(arc, point)),
+            CurveRef::Circle(circle) => {
+                let t = circle.center.angle_to(point).rem_euclid(std::f64::consts::TAU);
+                let on_circle = self.point_at(t);
+                Some(Closest {
+                    point: on_circle,
+                    parameter: t,
+                    distance_squared: on_circle.distance_squared_to(point),
+                })
+            }
+            _ => project(|t| self.point_at(t), |t| self.derivative_at(t), &self.samples(), point),
+        }
+    }
+
+    fn sample_parameters(&self) -> Vec<f64> {
+        match self {
+            CurveRef::Segment(_) => even(0.0, 1.0, SEGMENT_SAMPLES),
+            _ => self.samples(),
+        }
+    }
+}
+
+macro_rules! impl_closest_via_curve_ref {
+    ($($ty:ty),*) => {
+        $(
+            impl ClosestPoint for $ty {
+                fn point_at_parameter(&self, t: f64) -> Point2D {
+                    CurveRef::from(self).point_at(t)
+                }
+
+                fn closest_to_point(&self, point: &Point2D) -> Option<Closest> {
+                    CurveRef::from(self).closest_to_point(point)
+                }
+
+                fn sample_parameters(&self) -> Vec<f64> {
+                    CurveRef::from(self).sample_parameters()
+                }
+            }
+        )*
+    };
+}
+
+impl_closest_via_curve_ref!(
+    LineSegment2D,
+    Arc2D,
+    Circle2D,
+    Ellipse2D,
+    EllipticalArc2D,
+    Parabola2D,
+    Hyperbola2D,
+    BSpline,
+    NurbsCurve
+);
+
+impl ClosestPoint for Point2D {
+    fn point_at_parameter(&self, _t: f64) -> Point2D {
+        *self
+    }
+
+    fn closest_to_point(&self, point: &Point2D) -> Option<Closest> {
+        Some(Closest {
+            point: *self,
+            parameter: 0.0,
+            distance_squared: self.distance_squared_to(point),
+        })
+    }
+
+    fn sample_parameters(&self) -> Vec<f64> {
+        vec![0.0]
+    }
+}
+
+impl ClosestPoint for Line2D {
+    fn point_at_parameter(&self, t: f64) -> Point2D {
+        self.point_at(t)
+    }
+
+    fn closest_to_point(&self, point: &Point2D) -> Option<Closest> {
+        let t = (point.x - self.point.x) * self.direction.x + (point.y - self.point.y) * self.direction.y;
+        let on_line = self.point_at(t);
+        Some(Closest {
+            point: on_line,
+            parameter: t,
+            distance_squared: on_line.distance_squared_to(point),
+        })
+    }
+
+    fn sample_parameters(&self) -> Vec<f64> {
+        Vec::new()
+    }
+}
+
+impl ClosestPoint for FitSegment {
+    fn point_at_parameter(&self, t: f64) -> Point2D {
+        match self {
+            FitSegment::Line(line) => line.point_at_parameter(t),
+            FitSegment::Arc(arc) => arc.point_at_parameter(t),
+        }
+    }
+
+    fn closest_to_point(&self, point: &Point2D) -> Option<Closest> {
+        match self {
+            FitSegment::Line(line) => line.closest_to_point(point),
+            FitSegment::Arc(arc) => arc.closest_to_point(point),
+        }
+    }
+
+    fn sample_parameters(&self) -> Vec<f64> {
+        match self {
+            FitSegment::Line(line) => line.sample_parameters(),
+            FitSegment::Arc(arc) => arc.sample_parameters(),
+        }
+    }
+}
+
+/// A chain of lines and arcs parameterized by segment index plus fraction
+struct Chain<'a> {
+    segments: &'a [FitSegment],
+    /// The point of a chain with no segments
+    lone: Option<Point2D>,
+}
+
+impl Chain<'_> {
+    /// Range of the segment's own parameter
+    fn sweep(segment: &FitSegment) -> f64 {
+        match segment {
+            FitSegment::Line(_) => 1.0,
+            FitSegment::Arc(arc) => arc.sweep_angle(),
+        }
+    }
+
+    fn point_at(&self, t: f64) -> Option<Point2D> {
+        let Some(last) = self.segments.len().checked_sub(1) else {
+            return self.lone;
+        };
+        let t = t.clamp(0.0, self.segments.len() as f64);
+        let index = (t.floor() as usize).min(last);
+        let segment = &self.segments[index];
+        Some(segment.point_at_parameter((t - index as f64) * Self::sweep(segment)))
+    }
+
+    fn closest(&self, point: &Point2D) -> Option<Closest> {
+        if self.segments.is_empty() {
+            return self.lone.and_then(|lone| lone.closest_to_point(point));
+        }
+        self.segments
+            .iter()
+            .enumerate()
+            .filter_map(|(index, segment)| {
+                let closest = segment.closest_to_point(point)?;
+                let sweep = Self::sweep(segment);
+                let fraction = if sweep > 0.0 { closest.parameter / sweep } else { 0.0 };
+                Some(Closest {
+                    parameter: index as f64 + fraction,
+                    ..closest
+                })
+            })
+            .min_by(|a, b| a.distance_squared.total_cmp(&b.distance_squared))
+    }
+
+    fn samples(&self) -> Vec<f64> {
+        if self.segments.is_empty() {
+            return if self.lone.is_some() { vec![0.0] } else { Vec::new() };
+        }
+        let mut samples: Vec<f64> = self
+            .segments
+            .iter()
+            .enumerate()
+            .flat_map(|(index, segment)| {
+                let sweep = Self::sweep(segment);
+                let mut params = segment.sample_parameters();
+                params.pop();
+                params
+                    .into_iter()
+                    .map(move |t| index as f64 + if sweep > 0.0 { t / sweep } else { 0.0 })
+            })
+            .collect();
+        samples.push(self.segments.len() as f64);
+        samples
+    }
+}
+
+fn lines(segments: Vec<LineSegment2D>) -> Vec<FitSegment> {
+    segments.into_iter().map(FitSegment::Line).collect()
+}
+
+impl ClosestPoint for Polyline2D {
+    fn point_at_parameter(&self, t: f64) -> Point2D {
+        let segments = lines(self.segments());
+        let chain = Chain {
+            segments: &segments,
+            lone: self.vertices.first().copied(),
+        };
+        chain.point_at(t).unwrap_or_default()
+    }
+
+    fn closest_to_point(&self, point: &Point2D) -> Option<Closest> {
+        let segments = lines(self.segments());
+        Chain {
+            segments: &segments,
+            lone: self.vertices.first().copied(),
+        }
+        .closest(point)
+    }
+
+    fn sample_parameters(&self) -> Vec<f64> {
+        let segments = lines(self.segments());
+        Chain {
+            segments: &segments,
+            lone: self.vertices.first().copied(),
+        }
+        .samples()
+    }
+}
+
+impl ClosestPoint for ArcPolyline {
+    fn point_at_parameter(&self, t: f64) -> Point2D {
+        let segments = self.segments();
+        let chain = Chain {
+            segments: &segments,
+            lone: self.vertices.first().map(|v| v.point),
+        };
+        chain.point_at(t).unwrap_or_default()
+    }
+
+    fn closest_to_point(&self, point: &Point2D) -> Option<Closest> {
+        let segments = self.segments();
+        Chain {
+            segments: &segments,
+            lone: self.vertices.first().map(|v| v.point),
+        }
+        .closest(point)
+    }
+
+    fn sample_parameters(&self) -> Vec<f64> {
+        let segments = self.segments();
+        Chain {
+            segments: &segments,
+            lone: self.vertices.first().map(|v| v.point),
+        }
+        .samples()
+    }
+}
+
+/// Edges of a polygon's outer boundary followed by those of its holes
+fn boundary(polygon: &Polygon2D) -> Vec<FitSegment> {
+    let mut edges = lines(polygon.edges());
+    for hole in polygon.holes.iter().filter(|hole| hole.len() >= 2) {
+        edges
+            .extend((0..hole.len()).map(|i| FitSegment::Line(LineSegment2D::new(hole[i], hole[(i + 1) % hole.len()]))));
+    }
+    edges
+}
+
+/// Distances are to the boundary, so points inside the polygon are not at
+/// distance zero
+impl ClosestPoint for Polygon2D {
+    fn point_at_parameter(&self, t: f64) -> Point2D {
+        let edges = boundary(self);
+        let chain = Chain {
+            segments: &edges,
+            lone: self.vertices.first().copied(),
+        };
+        chain.point_at(t).unwrap_or_default()
+    }
+
+    fn closest_to_point(&self, point: &Point2D) -> Option<Closest> {
+        let edges = boundary(self);
+        Chain {
+            segments: &edges,
+            lone: self.vertices.first().copied(),
+        }
+        .closest(point)
+    }
+
+    fn sample_parameters(&self) -> Vec<f64> {
+        let edges = boundary(self);
+        Chain {
+            segments: &edges,
+            lone: self.vertices.first().copied(),
+        }
+        .samples()
+    }
+}
+
+impl ClosestPoint for BezierCurve {
+    fn point_at_parameter(&self, t: f64) -> Point2D {
+        self.evaluate(t)
+    }
+
+    fn closest_to_point(&self, point: &Point2D) -> Option<Closest> {
+        if self.control_points.is_empty() {
+            return None;
+        }
+        let derivative = self.derivative();
+        project(
+            |t| self.evaluate(t),
+            |t| derivative.as_ref().map_or_else(Point2D::origin, |d| d.evaluate(t)),
+            &self.sample_parameters(),
+            point,
+        )
+    }
+
+    fn sample_parameters(&self) -> Vec<f64> {
+        even(0.0, 1.0, BEZIER_SAMPLES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    const TOL: f64 = 1e-9;
+
+    /// Squared distance to the closest of 100k points evenly spaced in parameter
+    fn brute_force(entity: &dyn ClosestPoint, lo: f64, hi: f64, point: &Point2D) -> f64 {
+        (0..=100_000)
+            .map(|i| entity.point_at_parameter(lo + (hi - lo) * i as f64 / 100_000.0))
+            .map(|p| p.distance_squared_to(point))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn test_exact_projections() {
+        let segment = LineSegment2D::new(Point2D::new(0.0, 0.0), Point2D::new(10.0, 0.0));
+        let closest = segment.closest_to_point(&Point2D::new(4.0, 3.0)).unwrap();
+        assert!((closest.parameter - 0.4).abs() < TOL);
+        assert!((closest.distance() - 3.0).abs() < TOL);
+        assert_eq!(
+            segment.closest_to_point(&Point2D::new(-5.0, 0.0)).unwrap().point,
+            segment.start
+        );
+
+        // Clockwise quarter arc from 12 o'clock to 3 o'clock
+        let arc = Arc2D::new(Point2D::new(0.0, 0.0), 2.0, FRAC_PI_2, 0.0, false);
+        let closest = arc.closest_to_point(&Point2D::new(3.0, 3.0)).unwrap();
+        assert!((closest.parameter - PI / 4.0).abs() < TOL);
+        assert!((closest.distance() - (18f64.sqrt() - 2.0)).abs() < TOL);
+        // Outside the sweep the nearer end wins
+        let closest = arc.closest_to_point(&Point2D::new(-1.0, 5.0)).unwrap();
+        assert_eq!(closest.parameter, 0.0);
+
+        let square = Polyline2D::closed(vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(2.0, 0.0),
+            Point2D::new(2.0, 2.0),
+            Point2D::new(0.0, 2.0),
+        ]);
+        let closest = square.closest_to_point(&Point2D::new(3.0, 1.5)).unwrap();
+        assert!((closest.parameter - 1.75).abs() < TOL);
+        assert!((square.point_at_parameter(closest.parameter).distance_to(&closest.point)) < TOL);
+
+        let line = Line2D::from_points(Point2D::new(0.0, 1.0), Point2D::new(1.0, 2.0)).unwrap();
+        let closest = line.closest_to_point(&Point2D::new(-3.0, 0.0)).unwrap();
+        assert!((closest.distance_squared - 2.0).abs() < TOL);
+        assert!(Polyline2D::new(Vec::new(), false)
+            .closest_to_point(&Point2D::origin())
+            .is_none());
+    }
+
+    #[test]
+    fn test_numeric_projections() {
+        let ellipse = Ellipse2D::new(Point2D::new(1.0, -1.0), 5.0, 2.0, 0.3);
+        let spline = BSpline::new(
+            vec![
+                Point2D::new(0.0, 0.0),
+                Point2D::new(1.0, 3.0),
+                Point2D::new(3.0, -2.0),
+                Point2D::new(5.0, 4.0),
+                Point2D::new(7.0, 0.0),
+            ],
+            vec![0.0, 0.0, 0.0, 0.0, 0.5, 1.0, 1.0, 1.0, 1.0],
+            3,
+        )
+        .unwrap();
+        let bezier = BezierCurve::new(vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(2.0, 4.0),
+            Point2D::new(4.0, -4.0),
+            Point2D::new(6.0, 0.0),
+        ]);
+
+        for query in [Point2D::new(0.5, -0.5), Point2D::new(4.0, 1.0), Point2D::new(-6.0, 3.0)] {
+            let closest = ellipse.closest_to_point(&query).unwrap();
+            assert!(closest.distance_squared <= brute_force(&ellipse, 0.0, 2.0 * PI, &query) + 1e-12);
+            let closest = spline.closest_to_point(&query).unwrap();
+            assert!(closest.distance_squared <= brute_force(&spline, 0.0, 1.0, &query) + 1e-12);
+            let closest = bezier.closest_to_point(&query).unwrap();
+            assert!(closest.distance_squared <= brute_force(&bezier, 0.0, 1.0, &query) + 1e-12);
+            // The offset to the query is normal to the curve away from its ends
+            if closest.parameter > 0.0 && closest.parameter < 1.0 {
+                let tangent = bezier.derivative().unwrap().evaluate(closest.parameter);
+                assert!((query - closest.point).dot(&tangent.normalize()).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_closest_between_entities() {
+        let segment = LineSegment2D::new(Point2D::new(-5.0, 4.0), Point2D::new(5.0, 4.0));
+        let circle = Circle2D::new(Point2D::new(1.0, 0.0), 1.5);
+        let pair = segment.closest_to_entity(&circle).unwrap();
+        assert!((pair.distance() - 2.5).abs() < TOL);
+        assert!(pair.first.point.distance_to(&Point2D::new(1.0, 4.0)) < 1e-6);
+        assert!(pair.second.point.distance_to(&Point2D::new(1.0, 1.5)) < 1e-6);
+
+        // Crossing curves meet
+        let arc = Arc2D::new(Point2D::new(0.0, 0.0), 5.0, 0.0, PI, true);
+        let pair = closest_between(&arc, &segment).unwrap();
+        assert!(pair.distance() < 1e-9);
+        assert!((pair.second.point.y - 4.0).abs() < 1e-9);
+
+        // Unbounded lines against bounded entities and each other
+        let line = Line2D::from_points(Point2D::new(0.0, -3.0), Point2D::new(1.0, -3.0)).unwrap();
+        let pair = line.closest_to_entity(&circle).unwrap();
+        assert!((pair.distance() - 1.5).abs() < 1e-9);
+        assert!((pair.first.parameter - 1.0).abs() < 1e-6);
+
+        let crossing = Line2D::from_points(Point2D::new(2.0, 0.0), Point2D::new(3.0, 1.0)).unwrap();
+        let pair = line.closest_to_entity(&crossing).unwrap();
+        assert!(pair.distance() < TOL);
+        assert!(pair.first.point.distance_to(&Point2D::new(-1.0, -3.0)) < TOL);
+        let parallel = Line2D::from_points(Point2D::new(0.0, 2.0), Point2D::new(-1.0, 2.0)).unwrap();
+        assert!((line.closest_to_entity(&parallel).unwrap().distance() - 5.0).abs() < TOL);
+    }
+}
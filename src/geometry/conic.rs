@@ -0,0 +1,848 @@
+//! Parabolas, hyperbolas and general conic sections
+//!
+//! [`Parabola2D`] and [`Hyperbola2D`] are bounded pieces of a parabola and of
+//! one branch of a hyperbola, parameterized the way STEP defines them.
+//! Together with the ellipses in [`arc`](crate::geometry::arc) they cover
+//! every non-degenerate conic, so IGES and STEP conic edges can be kept
+//! exact instead of being flattened to polylines.
+//!
+//! [`Conic2D`] is the implicit form Ax² + Bxy + Cy² + Dx + Ey + F = 0 that
+//! IGES stores. It classifies itself, recovers the ellipse, parabola or
+//! hyperbola it describes, and intersects lines and other conics
+//! algebraically: one conic is written as a rational quadratic curve and
+//! substituted into the other, leaving a polynomial of degree at most four
+//! whose real roots are isolated between the roots of its derivative.
+//! Tangent contacts are double roots with no sign change, so the
+//! derivative's roots are kept too when they put the point on the other
+//! conic.
+
+use crate::core::precision::{EPSILON, EPSILON_FINE};
+use crate::geometry::arc::{Circle2D, Ellipse2D, EllipticalArc2D};
+use crate::geometry::convert::{ellipse_to_nurbs, elliptical_arc_to_nurbs, hyperbola_to_nurbs, parabola_to_nurbs};
+use crate::geometry::curve::NurbsCurve;
+use crate::geometry::line::Line2D;
+use crate::geometry::point::Point2D;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Bisection steps when isolating a polynomial root
+const MAX_BISECTIONS: usize = 200;
+
+/// Piece of a parabola
+///
+/// In the parabola's frame, `vertex` at the origin and the axis along +x
+/// turned by `rotation`, the point at parameter t is (f t², 2f t) for focal
+/// length f, so the focus is at (f, 0).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Parabola2D {
+    /// Vertex
+    pub vertex: Point2D,
+    /// Distance from the vertex to the focus
+    pub focal_length: f64,
+    /// Direction of the axis, from the vertex towards the focus, in radians
+    pub rotation: f64,
+    /// Parameter at the start
+    pub start: f64,
+    /// Parameter at the end
+    pub end: f64,
+}
+
+impl Parabola2D {
+    /// Create a new parabola piece
+    pub fn new(vertex: Point2D, focal_length: f64, rotation: f64, start: f64, end: f64) -> Self {
+        Self {
+            vertex,
+            focal_length,
+            rotation,
+            start,
+            end,
+        }
+    }
+
+    /// Point at parameter `t`
+    pub fn point_at(&self, t: f64) -> Point2D {
+        let f = self.focal_length;
+        self.vertex + Point2D::new(f * t * t, 2.0 * f * t).rotate(self.rotation)
+    }
+
+    /// Derivative with respect to the parameter at `t`
+    pub fn derivative_at(&self, t: f64) -> Point2D {
+        Point2D::new(2.0 * self.focal_length * t, 2.0 * self.focal_length).rotate(self.rotation)
+    }
+
+    pub fn start_point(&self) -> Point2D {
+        self.point_at(self.start)
+    }
+
+    pub fn end_point(&self) -> Point2D {
+        self.point_at(self.end)
+    }
+
+    pub fn focus(&self) -> Point2D {
+        self.vertex + Point2D::from_polar(self.focal_length, self.rotation)
+    }
+
+    /// Exact polynomial quadratic NURBS form on [0, 1]
+    pub fn to_nurbs(&self) -> NurbsCurve {
+        parabola_to_nurbs(self)
+    }
+}
+
+/// Piece of one branch of a hyperbola
+///
+/// In the hyperbola's frame, `center` at the origin and the transverse axis
+/// along +x turned by `rotation`, the point at parameter t is
+/// (a cosh t, b sinh t): the branch opening towards +x.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Hyperbola2D {
+    /// Center, where the asymptotes cross
+    pub center: Point2D,
+    /// Semi-transverse axis length, center to vertex
+    pub semi_major: f64,
+    /// Semi-conjugate axis length
+    pub semi_minor: f64,
+    /// Direction of the transverse axis, towards the branch, in radians
+    pub rotation: f64,
+    /// Parameter at the start
+    pub start: f64,
+    /// Parameter at the end
+    pub end: f64,
+}
+
+impl Hyperbola2D {
+    /// Create a new hyperbola piece
+    pub fn new(center: Point2D, semi_major: f64, semi_minor: f64, rotation: f64, start: f64, end: f64) -> Self {
+        Self {
+            center,
+            semi_major,
+            semi_minor,
+            rotation,
+            start,
+            end,
+        }
+    }
+
+    /// Point at parameter `t`
+    pub fn point_at(&self, t: f64) -> Point2D {
+        self.center + Point2D::new(self.semi_major * t.cosh(), self.semi_minor * t.sinh()).rotate(self.rotation)
+    }
+
+    /// Derivative with respect to the parameter at `t`
+    pub fn derivative_at(&self, t: f64) -> Point2D {
+        Point2D::new(self.semi_major * t.sinh(), self.semi_minor * t.cosh()).rotate(self.rotation)
+    }
+
+    pub fn start_point(&self) -> Point2D {
+        self.point_at(self.start)
+    }
+
+    pub fn end_point(&self) -> Point2D {
+        self.point_at(self.end)
+    }
+
+    pub fn vertex(&self) -> Point2D {
+        self.point_at(0.0)
+    }
+
+    /// Foci, the one inside this branch first
+    pub fn foci(&self) -> (Point2D, Point2D) {
+        let c = self.semi_major.hypot(self.semi_minor);
+        let offset = Point2D::from_polar(c, self.rotation);
+        (self.center + offset, self.center - offset)
+    }
+
+    pub fn eccentricity(&self) -> f64 {
+        self.semi_major.hypot(self.semi_minor) / self.semi_major
+    }
+
+    /// Exact rational quadratic NURBS form on [0, 1]
+    pub fn to_nurbs(&self) -> NurbsCurve {
+        hyperbola_to_nurbs(self)
+    }
+}
+
+/// What a [`Conic2D`] describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ConicKind {
+    Ellipse,
+    Parabola,
+    Hyperbola,
+    /// A point, one or two lines, or no real points at all
+    Degenerate,
+}
+
+/// A bounded piece of a conic, as the curve type for its kind
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ConicArc {
+    Ellipse(Ellipse2D),
+    Elliptical(EllipticalArc2D),
+    Parabolic(Parabola2D),
+    Hyperbolic(Hyperbola2D),
+}
+
+impl ConicArc {
+    pub fn start_point(&self) -> Point2D {
+        match self {
+            ConicArc::Ellipse(ellipse) => ellipse.point_at_angle(0.0),
+            ConicArc::Elliptical(arc) => arc.point_at_angle(arc.start_angle),
+            ConicArc::Parabolic(parabola) => parabola.start_point(),
+            ConicArc::Hyperbolic(hyperbola) => hyperbola.start_point(),
+        }
+    }
+
+    pub fn end_point(&self) -> Point2D {
+        match self {
+            ConicArc::Ellipse(ellipse) => ellipse.point_at_angle(0.0),
+            ConicArc::Elliptical(arc) => arc.point_at_angle(arc.end_angle),
+            ConicArc::Parabolic(parabola) => parabola.end_point(),
+            ConicArc::Hyperbolic(hyperbola) => hyperbola.end_point(),
+        }
+    }
+
+    /// Exact NURBS form on [0, 1]
+    pub fn to_nurbs(&self) -> NurbsCurve {
+        match self {
+            ConicArc::Ellipse(ellipse) => ellipse_to_nurbs(ellipse),
+            ConicArc::Elliptical(arc) => elliptical_arc_to_nurbs(arc),
+            ConicArc::Parabolic(parabola) => parabola.to_nurbs(),
+            ConicArc::Hyperbolic(hyperbola) => hyperbola.to_nurbs(),
+        }
+    }
+}
+
+/// Conic in implicit form: a x² + b xy + c y² + d x + e y + f = 0
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Conic2D {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+/// A non-degenerate conic in its own frame
+#[derive(Debug, Clone, Copy)]
+enum Canonical {
+    Ellipse(Ellipse2D),
+    /// Vertex, focal length and axis direction
+    Parabola(Point2D, f64, f64),
+    /// Center, semi-axes and transverse axis direction
+    Hyperbola(Point2D, f64, f64, f64),
+}
+
+/// Polynomial coefficients, constant term first
+type Poly = Vec<f64>;
+
+impl Conic2D {
+    pub fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    pub fn from_circle(circle: &Circle2D) -> Self {
+        let r2 = circle.radius * circle.radius;
+        Self::from_frame(circle.center, 0.0, [1.0, 1.0, 0.0, 0.0, -r2])
+    }
+
+    pub fn from_ellipse(ellipse: &Ellipse2D) -> Self {
+        let (a, b) = (ellipse.semi_major, ellipse.semi_minor);
+        Self::from_frame(
+            ellipse.center,
+            ellipse.rotation,
+            [b * b, a * a, 0.0, 0.0, -a * a * b * b],
+        )
+    }
+
+    /// The whole parabola the piece lies on
+    pub fn from_parabola(parabola: &Parabola2D) -> Self {
+        Self::from_frame(
+            parabola.vertex,
+            parabola.rotation,
+            [0.0, 1.0, -4.0 * parabola.focal_length, 0.0, 0.0],
+        )
+    }
+
+    /// Both branches of the hyperbola the piece lies on
+    pub fn from_hyperbola(hyperbola: &Hyperbola2D) -> Self {
+        let (a, b) = (hyperbola.semi_major, hyperbola.semi_minor);
+        Self::from_frame(
+            hyperbola.center,
+            hyperbola.rotation,
+            [b * b, -a * a, 0.0, 0.0, -a * a * b * b],
+        )
+    }
+
+    /// The conic [a, c, d, e, f] written in a frame at `origin` turned by
+    /// `rotation`, with no xy term there
+    fn from_frame(origin: Point2D, rotation: f64, [la, lc, ld, le, lf]: [f64; 5]) -> Self {
+        let (sin, cos) = rotation.sin_cos();
+        // Local coordinates are x' = cos u + sin v, y' = -sin u + cos v
+        // for u, v relative to the origin
+        let a = la * cos * cos + lc * sin * sin;
+        let b = 2.0 * cos * sin * (la - lc);
+        let c = la * sin * sin + lc * cos * cos;
+        let d = ld * cos - le * sin;
+        let e = ld * sin + le * cos;
+        let (x0, y0) = (origin.x, origin.y);
+        Self {
+            a,
+            b,
+            c,
+            d: d - 2.0 * a * x0 - b * y0,
+            e: e - 2.0 * c * y0 - b * x0,
+            f: lf + a * x0 * x0 + b * x0 * y0 + c * y0 * y0 - d * x0 - e * y0,
+        }
+    }
+
+    /// Value of the implicit function at `p`; zero on the conic
+    pub fn value_at(&self, p: Point2D) -> f64 {
+        let Self { a, b, c, d, e, f } = *self;
+        a * p.x * p.x + b * p.x * p.y + c * p.y * p.y + d * p.x + e * p.y + f
+    }
+
+    /// Gradient of the implicit function at `p`, normal to the conic there
+    pub fn gradient_at(&self, p: Point2D) -> Point2D {
+        Point2D::new(
+            2.0 * self.a * p.x + self.b * p.y + self.d,
+            self.b * p.x + 2.0 * self.c * p.y + self.e,
+        )
+    }
+
+    /// First-order estimate of the distance from `p` to the conic
+    fn distance_estimate(&self, p: Point2D) -> f64 {
+        let gradient = self.gradient_at(p).distance_to_origin();
+        if gradient == 0.0 {
+            return if self.value_at(p) == 0.0 { 0.0 } else { f64::INFINITY };
+        }
+        self.value_at(p).abs() / gradient
+    }
+
+    pub fn kind(&self) -> ConicKind {
+        match self.canonical() {
+            Some(Canonical::Ellipse(_)) => ConicKind::Ellipse,
+            Some(Canonical::Parabola(..)) => ConicKind::Parabola,
+            Some(Canonical::Hyperbola(..)) => ConicKind::Hyperbola,
+            None => ConicKind::Degenerate,
+        }
+    }
+
+    /// Coefficients [a, c, d, e] in axes turned by `theta`, ignoring the xy
+    /// term left over
+    fn turned(&self, theta: f64) -> [f64; 4] {
+        let (sin, cos) = theta.sin_cos();
+        [
+            self.a * cos * cos + self.b * cos * sin + self.c * sin * sin,
+            self.a * sin * sin - self.b * cos * sin + self.c * cos * cos,
+            self.d * cos + self.e * sin,
+            -self.d * sin + self.e * cos,
+        ]
+    }
+
+    /// The ellipse, parabola or hyperbola, or `None` if degenerate
+    fn canonical(&self) -> Option<Canonical> {
+        let scale = self.a.abs().max(self.b.abs()).max(self.c.abs());
+        if scale == 0.0 {
+            return None;
+        }
+        // Turn the axes to remove the xy term
+        let mut theta = 0.5 * self.b.atan2(self.a - self.c);
+        let [a, mut c, mut d, mut e] = self.turned(theta);
+        let flat = EPSILON_FINE * scale;
+
+        if a.abs() > flat && c.abs() > flat {
+            let (x0, y0) = (-d / (2.0 * a), -e / (2.0 * c));
+            let k = a * x0 * x0 + c * y0 * y0 - self.f;
+            if k.abs() <= EPSILON_FINE * (a.abs() * x0 * x0 + c.abs() * y0 * y0 + self.f.abs()) {
+                return None;
+            }
+            let center = Point2D::new(x0, y0).rotate(theta);
+            let (u, v) = (k / a, k / c);
+            return match (u > 0.0, v > 0.0) {
+                (true, true) if u >= v => Some(Canonical::Ellipse(Ellipse2D::new(center, u.sqrt(), v.sqrt(), theta))),
+                (true, true) => Some(Canonical::Ellipse(Ellipse2D::new(
+                    center,
+                    v.sqrt(),
+                    u.sqrt(),
+                    theta + PI / 2.0,
+                ))),
+                (true, false) => Some(Canonical::Hyperbola(center, u.sqrt(), (-v).sqrt(), theta)),
+                (false, true) => Some(Canonical::Hyperbola(center, v.sqrt(), (-u).sqrt(), theta + PI / 2.0)),
+                (false, false) => None,
+            };
+        }
+
+        // Parabola: put its axis along x', leaving no x'² term
+        if a.abs() > c.abs() {
+            theta += PI / 2.0;
+            [_, c, d, e] = self.turned(theta);
+        }
+        if d.abs() <= EPSILON_FINE * (c.abs() + d.abs() + e.abs()) {
+            return None;
+        }
+        // c (y' - y0)² = -d (x' - x0)
+        let y0 = -e / (2.0 * c);
+        let x0 = (c * y0 * y0 - self.f) / d;
+        let vertex = Point2D::new(x0, y0).rotate(theta);
+        let focal = -d / (4.0 * c);
+        if focal > 0.0 {
+            Some(Canonical::Parabola(vertex, focal, theta))
+        } else {
+            Some(Canonical::Parabola(vertex, -focal, theta + PI))
+        }
+    }
+
+    /// The piece of the conic from `start` to `end`
+    ///
+    /// Ellipses run counterclockwise, as IGES conic arcs do, and give the
+    /// whole ellipse when the two points coincide. Points off the conic are
+    /// projected onto it. `None` for degenerate conics and for points on
+    /// different branches of a hyperbola.
+    pub fn arc_between(&self, start: Point2D, end: Point2D) -> Option<ConicArc> {
+        let local = |p: Point2D, origin: Point2D, rotation: f64| (p - origin).rotate(-rotation);
+        match self.canonical()? {
+            Canonical::Ellipse(ellipse) => {
+                if start.distance_to(&end) <= EPSILON {
+                    return Some(ConicArc::Ellipse(ellipse));
+                }
+                let angle = |p: Point2D| {
+                    let q = local(p, ellipse.center, ellipse.rotation);
+                    (q.y / ellipse.semi_minor).atan2(q.x / ellipse.semi_major)
+                };
+                Some(ConicArc::Elliptical(EllipticalArc2D::new(
+                    ellipse.center,
+                    ellipse.semi_major,
+                    ellipse.semi_minor,
+                    ellipse.rotation,
+                    angle(start),
+                    angle(end),
+                    true,
+                )))
+            }
+            Canonical::Parabola(vertex, focal, rotation) => {
+                let param = |p: Point2D| local(p, vertex, rotation).y / (2.0 * focal);
+                Some(ConicArc::Parabolic(Parabola2D::new(
+                    vertex,
+                    focal,
+                    rotation,
+                    param(start),
+                    param(end),
+                )))
+            }
+            Canonical::Hyperbola(center, a, b, mut rotation) => {
+                let (s, e) = (local(start, center, rotation), local(end, center, rotation));
+                if (s.x < 0.0) != (e.x < 0.0) {
+                    return None;
+                }
+                // Points on the other branch: turn the frame to face it
+                let flip = if s.x < 0.0 { -1.0 } else { 1.0 };
+                if s.x < 0.0 {
+                    rotation += PI;
+                }
+                let param = |q: Point2D| (flip * q.y / b).asinh();
+                Some(ConicArc::Hyperbolic(Hyperbola2D::new(
+                    center,
+                    a,
+                    b,
+                    rotation,
+                    param(s),
+                    param(e),
+                )))
+            }
+        }
+    }
+
+    /// Points where an infinite line meets the conic, in order along it
+    ///
+    /// A line touching the conic to within [`EPSILON`] gives one point.
+    pub fn intersect_line(&self, line: &Line2D) -> Vec<Point2D> {
+        let (p, u) = (line.point, Point2D::new(line.direction.x, line.direction.y));
+        let Self { a, b, c, d, e, .. } = *self;
+        // Value along the line as alpha s² + beta s + gamma
+        let alpha = a * u.x * u.x + b * u.x * u.y + c * u.y * u.y;
+        let beta = 2.0 * a * p.x * u.x + b * (p.x * u.y + p.y * u.x) + 2.0 * c * p.y * u.y + d * u.x + e * u.y;
+        let gamma = self.value_at(p);
+        let at = |s: f64| p + u * s;
+
+        if alpha.abs() <= EPSILON_FINE * (a.abs() + b.abs() + c.abs()) * u.dot(&u) {
+            // Parallel to a parabola's axis or a hyperbola's asymptote
+            return if beta == 0.0 {
+                Vec::new()
+            } else {
+                vec![at(-gamma / beta)]
+            };
+        }
+        let vertex = -beta / (2.0 * alpha);
+        let disc = beta * beta - 4.0 * alpha * gamma;
+        if disc <= 0.0 {
+            return if self.distance_estimate(at(vertex)) <= EPSILON {
+                vec![at(vertex)]
+            } else {
+                Vec::new()
+            };
+        }
+        let q = -(beta + beta.signum() * disc.sqrt()) / 2.0;
+        let (mut s1, mut s2) = (q / alpha, gamma / q);
+        if s1 > s2 {
+            std::mem::swap(&mut s1, &mut s2);
+        }
+        if at(s1).distance_to(&at(s2)) <= EPSILON {
+            return vec![at(vertex)];
+        }
+        vec![at(s1), at(s2)]
+    }
+
+    /// Points where two conics meet, up to four
+    ///
+    /// Points within [`EPSILON`] of each other are reported once, so a
+    /// tangency gives one point. Coincident conics share infinitely many
+    /// points and give none.
+    pub fn intersect_conic(&self, other: &Conic2D) -> Vec<Point2D> {
+        match (self.canonical(), other.canonical()) {
+            (Some(canonical), _) => other.meet(canonical),
+            (None, Some(canonical)) => self.meet(canonical),
+            (None, None) => Vec::new(),
+        }
+    }
+
+    /// Points of `curve` on this conic
+    fn meet(&self, curve: Canonical) -> Vec<Point2D> {
+        let mut points = Vec::new();
+        for (x, y, w, lo) in rational_pieces(curve) {
+            let point = |t: f64| {
+                let q = eval(&w, t);
+                Point2D::new(eval(&x, t) / q, eval(&y, t) / q)
+            };
+            let (poly, reference) = self.compose(&x, &y, &w);
+            let size = reference.iter().fold(0.0_f64, |m, r| m.max(r.abs()));
+            if poly.iter().all(|p| p.abs() <= EPSILON_FINE * size) {
+                // The curve lies on this conic
+                return Vec::new();
+            }
+            let mut params = poly_candidates(&poly, &reference, lo);
+            params.retain(|&t| self.distance_estimate(point(t)) <= EPSILON);
+            params.sort_by(f64::total_cmp);
+
+            // Near a tangency rounding may leave two close roots and the
+            // turning point between them: one contact if the curve stays on
+            // the conic halfway between
+            let mut best: Option<(f64, f64)> = None;
+            for t in params {
+                let gap = self.distance_estimate(point(t));
+                best = match best {
+                    Some((last, last_gap)) if self.distance_estimate(point(0.5 * (last + t))) <= EPSILON => {
+                        Some(if gap < last_gap { (t, gap) } else { (last, last_gap) })
+                    }
+                    Some((last, _)) => {
+                        points.push(point(last));
+                        Some((t, gap))
+                    }
+                    None => Some((t, gap)),
+                };
+            }
+            points.extend(best.map(|(t, _)| point(t)));
+        }
+        // The ellipse parameterization misses the point at tan(θ/2) = ∞
+        if let Canonical::Ellipse(ellipse) = curve {
+            let far = ellipse.point_at_angle(PI);
+            if self.distance_estimate(far) <= EPSILON {
+                points.push(far);
+            }
+        }
+
+        let mut unique: Vec<Point2D> = Vec::with_capacity(points.len());
+        for p in points {
+            match unique.iter_mut().find(|q| q.distance_to(&p) <= EPSILON) {
+                Some(q) if self.distance_estimate(p) < self.distance_estimate(*q) => *q = p,
+                Some(_) => {}
+                None => unique.push(p),
+            }
+        }
+        unique
+    }
+
+    /// This conic's value along the rational curve (x/w, y/w), times w², and
+    /// the same sum taken over absolute values to judge its size
+    fn compose(&self, x: &[f64], y: &[f64], w: &[f64]) -> (Poly, Poly) {
+        let terms = [
+            (self.a, x, x),
+            (self.b, x, y),
+            (self.c, y, y),
+            (self.d, x, w),
+            (self.e, y, w),
+            (self.f, w, w),
+        ];
+        let abs = |p: &[f64]| p.iter().map(|c| c.abs()).collect::<Poly>();
+        let mut poly = vec![0.0; 5];
+        let mut reference = vec![0.0; 5];
+        for (k, p, q) in terms {
+            for (i, (value, size)) in poly_mul(p, q).iter().zip(poly_mul(&abs(p), &abs(q))).enumerate() {
+                poly[i] += k * value;
+                reference[i] += k.abs() * size;
+            }
+        }
+        (poly, reference)
+    }
+}
+
+/// A canonical conic as rational quadratic pieces (x, y, w, lowest
+/// parameter); parameters run from the lowest to +∞
+fn rational_pieces(curve: Canonical) -> Vec<(Poly, Poly, Poly, f64)> {
+    // Local polynomial coordinates placed at `origin`, turned by `rotation`
+    let place = |origin: Point2D, rotation: f64, lx: [f64; 3], ly: [f64; 3], w: [f64; 3]| {
+        let (sin, cos) = rotation.sin_cos();
+        let x = (0..3).map(|i| origin.x * w[i] + cos * lx[i] - sin * ly[i]).collect();
+        let y = (0..3).map(|i| origin.y * w[i] + sin * lx[i] + cos * ly[i]).collect();
+        (x, y, w.to_vec())
+    };
+    match curve {
+        Canonical::Ellipse(e) => {
+            // u = tan(θ / 2): (a (1 - u²), 2b u) / (1 + u²)
+            let (a, b) = (e.semi_major, e.semi_minor);
+            let (x, y, w) = place(e.center, e.rotation, [a, 0.0, -a], [0.0, 2.0 * b, 0.0], [1.0, 0.0, 1.0]);
+            vec![(x, y, w, f64::NEG_INFINITY)]
+        }
+        Canonical::Parabola(vertex, f, rotation) => {
+            let (x, y, w) = place(vertex, rotation, [0.0, 0.0, f], [0.0, 2.0 * f, 0.0], [1.0, 0.0, 0.0]);
+            vec![(x, y, w, f64::NEG_INFINITY)]
+        }
+        Canonical::Hyperbola(center, a, b, rotation) => {
+            // w = e^t: (±a (w² + 1), b (w² - 1)) / 2w on each branch
+            [1.0, -1.0]
+                .into_iter()
+                .map(|side| {
+                    let (x, y, w) = place(
+                        center,
+                        rotation,
+                        [side * a, 0.0, side * a],
+                        [-b, 0.0, b],
+                        [0.0, 2.0, 0.0],
+                    );
+                    (x, y, w, 0.0)
+                })
+                .collect()
+        }
+    }
+}
+
+fn eval(poly: &[f64], t: f64) -> f64 {
+    poly.iter().rev().fold(0.0, |acc, &c| acc * t + c)
+}
+
+fn poly_mul(p: &[f64], q: &[f64]) -> Poly {
+    let mut product = vec![0.0; p.len() + q.len() - 1];
+    for (i, a) in p.iter().enumerate() {
+        for (j, b) in q.iter().enumerate() {
+            product[i + j] += a * b;
+        }
+    }
+    product
+}
+
+fn derivative(poly: &[f64]) -> Poly {
+    poly.iter().enumerate().skip(1).map(|(i, c)| c * i as f64).collect()
+}
+
+/// Parameters above `lo` that may be roots of `poly`: its real roots and
+/// its turning points, which are roots when the polynomial only touches
+/// zero there
+fn poly_candidates(poly: &[f64], reference: &[f64], lo: f64) -> Vec<f64> {
+    // Drop leading terms that are rounding noise
+    let mut degree = poly.len() - 1;
+    while degree > 0 && poly[degree].abs() <= EPSILON_FINE * reference[degree].max(f64::MIN_POSITIVE) {
+        degree -= 1;
+    }
+    let poly = &poly[..=degree];
+    if degree == 0 {
+        return Vec::new();
+    }
+    // Cauchy's bound on the magnitude of the roots
+    let bound = 1.0
+        + poly[..degree]
+            .iter()
+            .fold(0.0_f64, |m, c| m.max((c / poly[degree]).abs()));
+    let (lo, hi) = (lo.max(-bound), bound);
+
+    let turning = roots_between(&derivative(poly), lo, hi);
+    let mut candidates = roots_between(poly, lo, hi);
+    candidates.extend(turning);
+    candidates
+}
+
+/// Real roots of `poly` in (lo, hi), each found by bisection between
+/// consecutive roots of its derivative
+fn roots_between(poly: &[f64], lo: f64, hi: f64) -> Vec<f64> {
+    if poly.len() < 2 {
+        return Vec::new();
+    }
+    if poly.len() == 2 {
+        let root = -poly[0] / poly[1];
+        return if root > lo && root < hi { vec![root] } else { Vec::new() };
+    }
+    let mut ends = vec![lo];
+    ends.extend(roots_between(&derivative(poly), lo, hi));
+    ends.push(hi);
+
+    let mut roots = Vec::new();
+    for pair in ends.windows(2) {
+        let (mut a, mut b) = (pair[0], pair[1]);
+        let (fa, fb) = (eval(poly, a), eval(poly, b));
+        if fa == 0.0 {
+            roots.push(a);
+            continue;
+        }
+        if fa.signum() == fb.signum() {
+            continue;
+        }
+        for _ in 0..MAX_BISECTIONS {
+            let mid = 0.5 * (a + b);
+            if mid <= a || mid >= b {
+                break;
+            }
+            if eval(poly, mid).signum() == fa.signum() {
+                a = mid;
+            } else {
+                b = mid;
+            }
+        }
+        roots.push(0.5 * (a + b));
+    }
+    roots.retain(|&r| r > lo && r < hi);
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector2;
+
+    const TOL: f64 = 1e-9;
+
+    fn assert_points(mut found: Vec<Point2D>, mut expected: Vec<Point2D>, eps: f64) {
+        let key = |p: &Point2D| (p.x * 1e6).round() as i64 * 1_000_000_000 + (p.y * 1e6).round() as i64;
+        found.sort_by_key(key);
+        expected.sort_by_key(key);
+        assert_eq!(found.len(), expected.len(), "{found:?}");
+        for (p, q) in found.iter().zip(&expected) {
+            assert!(p.approx_eq_eps(q, eps), "{p:?} != {q:?}");
+        }
+    }
+
+    #[test]
+    fn test_parabola_and_hyperbola() {
+        let parabola = Parabola2D::new(Point2D::new(1.0, 1.0), 0.5, PI / 2.0, -2.0, 2.0);
+        // Opening upwards: y = 1 + x'² / 2 for x' = x - 1
+        assert!(parabola.point_at(1.0).approx_eq_eps(&Point2D::new(0.0, 1.5), TOL));
+        assert!(parabola.focus().approx_eq_eps(&Point2D::new(1.0, 1.5), TOL));
+        let conic = Conic2D::from_parabola(&parabola);
+        assert_eq!(conic.kind(), ConicKind::Parabola);
+        for t in [-2.0, 0.3, 1.7] {
+            assert!(conic.value_at(parabola.point_at(t)).abs() < TOL);
+        }
+
+        let hyperbola = Hyperbola2D::new(Point2D::origin(), 3.0, 4.0, 0.0, -1.0, 1.0);
+        assert!((hyperbola.eccentricity() - 5.0 / 3.0).abs() < TOL);
+        assert!(hyperbola.foci().0.approx_eq_eps(&Point2D::new(5.0, 0.0), TOL));
+        let conic = Conic2D::from_hyperbola(&hyperbola);
+        assert_eq!(conic.kind(), ConicKind::Hyperbola);
+        assert!(conic.value_at(hyperbola.point_at(0.8)).abs() < TOL);
+
+        let ellipse = Ellipse2D::new(Point2D::new(2.0, -1.0), 3.0, 1.0, 0.4);
+        assert_eq!(Conic2D::from_ellipse(&ellipse).kind(), ConicKind::Ellipse);
+        // x² - y² = 0 is a pair of lines
+        assert_eq!(
+            Conic2D::new(1.0, 0.0, -1.0, 0.0, 0.0, 0.0).kind(),
+            ConicKind::Degenerate
+        );
+        assert_eq!(Conic2D::new(1.0, 0.0, 1.0, 0.0, 0.0, 1.0).kind(), ConicKind::Degenerate);
+    }
+
+    #[test]
+    fn test_arc_between_recovers_curves() {
+        let ellipse = Ellipse2D::new(Point2D::new(2.0, -1.0), 3.0, 1.0, 0.4);
+        let conic = Conic2D::from_ellipse(&ellipse);
+        let (p, q) = (ellipse.point_at_angle(0.5), ellipse.point_at_angle(2.0));
+        match conic.arc_between(p, q) {
+            Some(ConicArc::Elliptical(arc)) => {
+                assert!((arc.semi_major - 3.0).abs() < TOL && (arc.semi_minor - 1.0).abs() < TOL);
+                assert!((arc.sweep_angle() - 1.5).abs() < 1e-8);
+            }
+            other => panic!("{other:?}"),
+        }
+        assert!(matches!(conic.arc_between(p, p), Some(ConicArc::Ellipse(_))));
+
+        let parabola = Parabola2D::new(Point2D::new(1.0, 1.0), 0.5, 2.0, -1.0, 2.0);
+        let arc = Conic2D::from_parabola(&parabola)
+            .arc_between(parabola.start_point(), parabola.end_point())
+            .unwrap();
+        assert!(matches!(arc, ConicArc::Parabolic(_)));
+        assert!(arc.start_point().approx_eq_eps(&parabola.start_point(), 1e-8));
+        assert!(arc.end_point().approx_eq_eps(&parabola.end_point(), 1e-8));
+
+        // The branch facing away from the hyperbola's own rotation
+        let hyperbola = Hyperbola2D::new(Point2D::new(-1.0, 0.0), 2.0, 1.0, PI + 0.3, -0.5, 1.5);
+        let conic = Conic2D::from_hyperbola(&hyperbola);
+        let arc = conic
+            .arc_between(hyperbola.start_point(), hyperbola.end_point())
+            .unwrap();
+        assert!(arc.start_point().approx_eq_eps(&hyperbola.start_point(), 1e-8));
+        assert!(arc.end_point().approx_eq_eps(&hyperbola.end_point(), 1e-8));
+        let other_branch = hyperbola.center * 2.0 - hyperbola.end_point();
+        assert!(conic.arc_between(hyperbola.start_point(), other_branch).is_none());
+    }
+
+    #[test]
+    fn test_line_intersections() {
+        let circle = Conic2D::from_circle(&Circle2D::new(Point2D::origin(), 2.0));
+        let across = Line2D::new(Point2D::new(-5.0, 1.0), Vector2::new(1.0, 0.0));
+        let root3 = 3.0_f64.sqrt();
+        assert_points(
+            circle.intersect_line(&across),
+            vec![Point2D::new(-root3, 1.0), Point2D::new(root3, 1.0)],
+            TOL,
+        );
+        let touching = Line2D::new(Point2D::new(-5.0, 2.0), Vector2::new(1.0, 0.0));
+        assert_points(circle.intersect_line(&touching), vec![Point2D::new(0.0, 2.0)], 1e-6);
+
+        // y = x² meets a vertical line once, along its axis
+        let parabola = Conic2D::new(1.0, 0.0, 0.0, 0.0, -1.0, 0.0);
+        let vertical = Line2D::new(Point2D::new(3.0, 0.0), Vector2::new(0.0, 1.0));
+        assert_points(parabola.intersect_line(&vertical), vec![Point2D::new(3.0, 9.0)], TOL);
+    }
+
+    #[test]
+    fn test_conic_intersections() {
+        let ellipse = Conic2D::from_ellipse(&Ellipse2D::new(Point2D::origin(), 2.0, 1.0, 0.0));
+        let turned = Conic2D::from_ellipse(&Ellipse2D::new(Point2D::origin(), 2.0, 1.0, PI / 2.0));
+        let s = (4.0_f64 / 5.0).sqrt();
+        let expected = vec![
+            Point2D::new(s, s),
+            Point2D::new(-s, s),
+            Point2D::new(s, -s),
+            Point2D::new(-s, -s),
+        ];
+        assert_points(ellipse.intersect_conic(&turned), expected, TOL);
+
+        // Circles touching the ellipse at the ends of its axes, one of them
+        // the point the tan(θ/2) parameterization misses
+        for (radius, touching) in [(1.0, Point2D::new(0.0, 1.0)), (2.0, Point2D::new(2.0, 0.0))] {
+            let circle = Conic2D::from_circle(&Circle2D::new(Point2D::origin(), radius));
+            let expected = vec![touching, -touching];
+            assert_points(ellipse.intersect_conic(&circle), expected.clone(), 1e-6);
+            assert_points(circle.intersect_conic(&ellipse), expected, 1e-6);
+        }
+
+        // y = x² - 1 against x² - y² = 1/4, two points on each branch
+        let parabola = Conic2D::new(1.0, 0.0, 0.0, 0.0, -1.0, -1.0);
+        let hyperbola = Conic2D::new(1.0, 0.0, -1.0, 0.0, 0.0, -0.25);
+        let hits = parabola.intersect_conic(&hyperbola);
+        assert_eq!(hits.len(), 4);
+        for p in &hits {
+            assert!(parabola.value_at(*p).abs() < 1e-8 && hyperbola.value_at(*p).abs() < 1e-8);
+        }
+        assert_eq!(hyperbola.intersect_conic(&parabola).len(), 4);
+
+        assert!(ellipse.intersect_conic(&ellipse).is_empty());
+        let far = Conic2D::from_circle(&Circle2D::new(Point2D::new(10.0, 0.0), 1.0));
+        assert!(ellipse.intersect_conic(&far).is_empty());
+    }
+}
@@ -0,0 +1,456 @@
+//! Conversions between curve representations
+//!
+//! Output formats without splines or ellipses (DXF R12, HPGL, G-code) need
+//! curves as simpler primitives, and NURBS-only consumers need the reverse:
+//! - [`flatten`] approximates a curve by a polyline whose chords stay within
+//!   a tolerance of it, adding points only where it bends
+//! - [`biarcs`] and [`to_arc_polyline`] approximate a curve by pairs of arcs
+//!   meeting with a common tangent, so the result has no corners where the
+//!   curve had none
+//! - [`arc_to_nurbs`], [`circle_to_nurbs`], [`ellipse_to_nurbs`],
+//!   [`elliptical_arc_to_nurbs`], [`parabola_to_nurbs`] and
+//!   [`hyperbola_to_nurbs`] represent conics exactly as rational quadratic
+//!   NURBS; [`BezierCurve::to_nurbs`] and [`BSpline::to_nurbs`]
+//!   promote polynomial curves
+//!
+//! Every curve [`CurveRef`] covers can be flattened or turned into biarcs.
+//! Deviations are measured at sample points: quarter points of each chord,
+//! and [`BIARC_SAMPLES`] points per arc pair.
+//!
+//! [`BezierCurve::to_nurbs`]: crate::geometry::curve::BezierCurve::to_nurbs
+//! [`BSpline::to_nurbs`]: crate::geometry::curve::BSpline::to_nurbs
+
+use crate::core::precision::EPSILON;
+use crate::geometry::arc::{Arc2D, Circle2D, Ellipse2D, EllipticalArc2D};
+use crate::geometry::conic::{Hyperbola2D, Parabola2D};
+use crate::geometry::curve::NurbsCurve;
+use crate::geometry::fitting::{ArcPolyline, FitSegment};
+use crate::geometry::intersect::CurveRef;
+use crate::geometry::line::LineSegment2D;
+use crate::geometry::point::Point2D;
+use std::f64::consts::{PI, TAU};
+
+/// Points along each arc pair checked against the curve
+pub const BIARC_SAMPLES: usize = 8;
+
+/// Deepest subdivision of one smooth piece of a curve; 2^-24 of a knot span
+/// is far below any useful tolerance
+const MAX_DEPTH: usize = 24;
+
+/// Approximate a curve by a polyline that stays within `tolerance` of it
+///
+/// Each smooth piece (knot span, or at most a quarter turn of a conic) is
+/// halved until its chords are within tolerance, so points cluster where
+/// the curve bends and straight stretches take a single chord.
+pub fn flatten<'a>(curve: impl Into<CurveRef<'a>>, tolerance: f64) -> Vec<Point2D> {
+    let curve = curve.into();
+    let tolerance = tolerance.max(EPSILON);
+    let breaks = curve.breaks();
+    let mut points = vec![curve.point_at(breaks[0])];
+    for pair in breaks.windows(2) {
+        flatten_between(&curve, pair[0], pair[1], tolerance, 0, &mut points);
+    }
+    points
+}
+
+fn flatten_between(curve: &CurveRef, a: f64, b: f64, tolerance: f64, depth: usize, points: &mut Vec<Point2D>) {
+    let end = curve.point_at(b);
+    let chord = LineSegment2D::new(curve.point_at(a), end);
+    let flat = [0.25, 0.5, 0.75]
+        .iter()
+        .all(|f| chord.distance_to_point(&curve.point_at(a + (b - a) * f)) <= tolerance);
+    if flat || depth >= MAX_DEPTH {
+        points.push(end);
+    } else {
+        let mid = (a + b) / 2.0;
+        flatten_between(curve, a, mid, tolerance, depth + 1, points);
+        flatten_between(curve, mid, b, tolerance, depth + 1, points);
+    }
+}
+
+/// Approximate a curve by arcs within `tolerance`, in pairs that share a
+/// tangent where they meet and match the curve's tangent at their ends
+///
+/// Lines stay lines; everything else is split at its smooth pieces and
+/// halved until each piece's biarc fits.
+pub fn biarcs<'a>(curve: impl Into<CurveRef<'a>>, tolerance: f64) -> Vec<FitSegment> {
+    let curve = curve.into();
+    if let CurveRef::Segment(line) = curve {
+        return vec![FitSegment::Line(*line)];
+    }
+    let tolerance = tolerance.max(EPSILON);
+    let mut segments = Vec::new();
+    for pair in curve.breaks().windows(2) {
+        biarcs_between(&curve, pair[0], pair[1], tolerance, 0, &mut segments);
+    }
+    segments
+}
+
+/// [`biarcs`] as a bulged polyline, closed if the curve is
+pub fn to_arc_polyline<'a>(curve: impl Into<CurveRef<'a>>, tolerance: f64) -> ArcPolyline {
+    let segments = biarcs(curve, tolerance);
+    let closed = match (segments.first(), segments.last()) {
+        (Some(first), Some(last)) => segments.len() > 1 && first.start().distance_to(&last.end()) <= EPSILON,
+        _ => false,
+    };
+    ArcPolyline::from_segments(&segments, closed)
+}
+
+fn biarcs_between(curve: &CurveRef, a: f64, b: f64, tolerance: f64, depth: usize, segments: &mut Vec<FitSegment>) {
+    let (start, end) = (curve.point_at(a), curve.point_at(b));
+    if start.distance_to(&end) < EPSILON {
+        return;
+    }
+    let chord = (end - start).normalize();
+    let tangent = |t: f64| {
+        let d = curve.derivative_at(t);
+        // A cusp has no tangent; the chord stands in for it
+        if d.distance_to_origin() < EPSILON {
+            chord
+        } else {
+            d.normalize()
+        }
+    };
+
+    let fitted = biarc(start, tangent(a), end, tangent(b));
+    if let Some(pair) = &fitted {
+        let fits = (1..BIARC_SAMPLES).all(|i| {
+            let p = curve.point_at(a + (b - a) * i as f64 / BIARC_SAMPLES as f64);
+            pair.iter().map(|s| segment_distance(s, p)).fold(f64::INFINITY, f64::min) <= tolerance
+        });
+        if fits || depth >= MAX_DEPTH {
+            segments.extend(pair.iter().filter(|s| s.length() > EPSILON));
+            return;
+        }
+    } else if depth >= MAX_DEPTH {
+        segments.push(FitSegment::Line(LineSegment2D::new(start, end)));
+        return;
+    }
+
+    let mid = (a + b) / 2.0;
+    biarcs_between(curve, a, mid, tolerance, depth + 1, segments);
+    biarcs_between(curve, mid, b, tolerance, depth + 1, segments);
+}
+
+/// Two arcs from `start` leaving along unit `t0` to `end` arriving along
+/// unit `t1`, meeting with a common tangent, with equal tangent lengths
+/// from each end to the joint
+fn biarc(start: Point2D, t0: Point2D, end: Point2D, t1: Point2D) -> Option<[FitSegment; 2]> {
+    let v = end - start;
+    let vt = v.dot(&(t0 + t1));
+    let denom = 2.0 * (1.0 - t0.dot(&t1));
+    let d = if denom < EPSILON {
+        // Parallel tangents
+        let along = v.dot(&t1);
+        if along.abs() < EPSILON {
+            return None;
+        }
+        v.dot(&v) / (4.0 * along)
+    } else {
+        (-vt + (vt * vt + denom * v.dot(&v)).sqrt()) / denom
+    };
+    if !d.is_finite() || d <= 0.0 {
+        return None;
+    }
+
+    let joint = (start + t0 * d + end - t1 * d) / 2.0;
+    Some([tangent_arc(start, t0, joint), reverse(tangent_arc(end, -t1, joint))])
+}
+
+/// Arc leaving `start` along unit `tangent` and ending at `end`; a line if
+/// `end` lies along the tangent
+fn tangent_arc(start: Point2D, tangent: Point2D, end: Point2D) -> FitSegment {
+    let chord = end - start;
+    let cross = tangent.cross(&chord);
+    if cross.abs() <= EPSILON * chord.distance_to_origin() {
+        return FitSegment::Line(LineSegment2D::new(start, end));
+    }
+    let normal = Point2D::new(-tangent.y, tangent.x) * cross.signum();
+    let radius = chord.dot(&chord) / (2.0 * cross.abs());
+    let center = start + normal * radius;
+    FitSegment::Arc(Arc2D::new(
+        center,
+        radius,
+        center.angle_to(&start),
+        center.angle_to(&end),
+        cross > 0.0,
+    ))
+}
+
+fn reverse(segment: FitSegment) -> FitSegment {
+    match segment {
+        FitSegment::Line(line) => FitSegment::Line(LineSegment2D::new(line.end, line.start)),
+        FitSegment::Arc(arc) => FitSegment::Arc(Arc2D::new(arc.center, arc.radius, arc.end_angle, arc.start_angle, !arc.ccw)),
+    }
+}
+
+fn segment_distance(segment: &FitSegment, p: Point2D) -> f64 {
+    match segment {
+        FitSegment::Line(line) => line.distance_to_point(&p),
+        FitSegment::Arc(arc) => {
+            if arc.contains_angle(arc.center.angle_to(&p)) {
+                (arc.center.distance_to(&p) - arc.radius).abs()
+            } else {
+                p.distance_to(&arc.start_point()).min(p.distance_to(&arc.end_point()))
+            }
+        }
+    }
+}
+
+/// An arc as an exact rational quadratic NURBS curve on [0, 1]
+pub fn arc_to_nurbs(arc: &Arc2D) -> NurbsCurve {
+    let sweep = if arc.ccw { arc.sweep_angle() } else { -arc.sweep_angle() };
+    conic(arc.start_angle, sweep, |u| arc.center + u * arc.radius)
+}
+
+/// A circle as an exact rational quadratic NURBS curve on [0, 1], starting
+/// and ending at angle 0
+pub fn circle_to_nurbs(circle: &Circle2D) -> NurbsCurve {
+    conic(0.0, TAU, |u| circle.center + u * circle.radius)
+}
+
+/// An ellipse as an exact rational quadratic NURBS curve on [0, 1],
+/// starting and ending at the end of its major axis
+pub fn ellipse_to_nurbs(ellipse: &Ellipse2D) -> NurbsCurve {
+    conic(0.0, TAU, |u| {
+        ellipse.center + Point2D::new(u.x * ellipse.semi_major, u.y * ellipse.semi_minor).rotate(ellipse.rotation)
+    })
+}
+
+/// An elliptical arc as an exact rational quadratic NURBS curve on [0, 1]
+pub fn elliptical_arc_to_nurbs(arc: &EllipticalArc2D) -> NurbsCurve {
+    let sweep = if arc.ccw { arc.sweep_angle() } else { -arc.sweep_angle() };
+    conic(arc.start_angle, sweep, |u| {
+        arc.center + Point2D::new(u.x * arc.semi_major, u.y * arc.semi_minor).rotate(arc.rotation)
+    })
+}
+
+/// A parabola piece as an exact quadratic NURBS curve on [0, 1]: a single
+/// Bezier segment, as a parabola needs no weights
+pub fn parabola_to_nurbs(parabola: &Parabola2D) -> NurbsCurve {
+    let (t0, t1, f) = (parabola.start, parabola.end, parabola.focal_length);
+    // The tangents at the ends meet at the middle control point
+    let middle = parabola.vertex + Point2D::new(f * t0 * t1, f * (t0 + t1)).rotate(parabola.rotation);
+    quadratic_pieces(parabola.start_point(), vec![(middle, 1.0, parabola.end_point())])
+}
+
+/// A hyperbola piece as an exact rational quadratic NURBS curve on [0, 1]
+///
+/// The weight of a piece grows as cosh of half its parameter span, so the
+/// piece is split where the span exceeds 2.
+pub fn hyperbola_to_nurbs(hyperbola: &Hyperbola2D) -> NurbsCurve {
+    let span = hyperbola.end - hyperbola.start;
+    let count = (span.abs() / 2.0 - EPSILON).ceil().max(1.0) as usize;
+    let step = span / count as f64;
+    let (a, b) = (hyperbola.semi_major, hyperbola.semi_minor);
+    let weight = (step / 2.0).cosh();
+    let pieces = (0..count)
+        .map(|i| {
+            let mid = hyperbola.start + step * (i as f64 + 0.5);
+            let middle = Point2D::new(a * mid.cosh(), b * mid.sinh()) / weight;
+            let end = hyperbola.point_at(hyperbola.start + step * (i + 1) as f64);
+            (hyperbola.center + middle.rotate(hyperbola.rotation), weight, end)
+        })
+        .collect();
+    quadratic_pieces(hyperbola.start_point(), pieces)
+}
+
+/// The image under `map` of the unit circle from angle `start` through
+/// signed `sweep`, as one rational quadratic segment per quarter turn
+///
+/// An affine `map` preserves the curve exactly: rational curves transform
+/// through their control points.
+fn conic(start: f64, sweep: f64, map: impl Fn(Point2D) -> Point2D) -> NurbsCurve {
+    let count = (sweep.abs() / (PI / 2.0) - EPSILON).ceil().max(1.0) as usize;
+    let step = sweep / count as f64;
+    let weight = (step / 2.0).cos();
+
+    let pieces = (0..count)
+        .map(|i| {
+            let from = start + step * i as f64;
+            (
+                map(Point2D::from_polar(1.0 / weight, from + step / 2.0)),
+                weight,
+                map(Point2D::from_polar(1.0, from + step)),
+            )
+        })
+        .collect();
+    quadratic_pieces(map(Point2D::from_polar(1.0, start)), pieces)
+}
+
+/// Rational quadratic segments from `first`, each given as its middle
+/// control point and weight and its end, joined on evenly spaced double
+/// knots over [0, 1]
+fn quadratic_pieces(first: Point2D, pieces: Vec<(Point2D, f64, Point2D)>) -> NurbsCurve {
+    let count = pieces.len();
+    let mut control_points = vec![first];
+    let mut weights = vec![1.0];
+    let mut knots = vec![0.0; 3];
+    for (i, (middle, weight, end)) in pieces.into_iter().enumerate() {
+        control_points.extend([middle, end]);
+        weights.extend([weight, 1.0]);
+        let knot = (i + 1) as f64 / count as f64;
+        knots.extend([knot, knot]);
+    }
+    knots.push(1.0);
+
+    NurbsCurve {
+        control_points,
+        weights,
+        knots,
+        degree: 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::conic::Conic2D;
+    use crate::geometry::curve::{BSpline, BezierCurve};
+
+    fn wave() -> BSpline {
+        BSpline::clamped(
+            (0..8).map(|i| Point2D::new(i as f64, if i % 2 == 0 { -1.0 } else { 1.0 })).collect(),
+            3,
+        )
+        .unwrap()
+    }
+
+    /// Largest distance from dense samples of `curve` to the polyline
+    fn deviation(curve: &BSpline, polyline: &[Point2D]) -> f64 {
+        curve
+            .to_polyline(2000)
+            .iter()
+            .map(|p| {
+                polyline
+                    .windows(2)
+                    .map(|w| LineSegment2D::new(w[0], w[1]).distance_to_point(p))
+                    .fold(f64::INFINITY, f64::min)
+            })
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn test_flatten_within_tolerance() {
+        let spline = wave();
+        let coarse = flatten(&spline, 0.01);
+        let fine = flatten(&spline, 0.0001);
+        assert!(deviation(&spline, &coarse) <= 0.01);
+        assert!(deviation(&spline, &fine) <= 0.0001);
+        assert!(coarse.len() < fine.len());
+        assert!(coarse[0].approx_eq(&spline.evaluate(0.0)));
+
+        // A straight spline needs one chord per knot span at most
+        let straight = BSpline::clamped((0..5).map(|i| Point2D::new(i as f64, 0.0)).collect(), 2).unwrap();
+        assert!(flatten(&straight, 1e-6).len() <= 4);
+
+        let circle = Circle2D::new(Point2D::new(1.0, 1.0), 2.0);
+        let points = flatten(&circle, 0.001);
+        assert!(points.iter().all(|p| (p.distance_to(&circle.center) - 2.0).abs() < 1e-9));
+        // Sagitta of 2π/n chords on radius 2 must be within 0.001
+        let n = (points.len() - 1) as f64;
+        assert!(2.0 * (1.0 - (PI / n).cos()) <= 0.001);
+    }
+
+    #[test]
+    fn test_biarcs_tangent_continuous() {
+        let spline = wave();
+        let tolerance = 0.001;
+        let segments = biarcs(&spline, tolerance);
+        assert!(segments.iter().all(|s| matches!(s, FitSegment::Arc(_))));
+
+        // Connected, with matching tangents at every joint
+        let direction = |s: &FitSegment, at_end: bool| match s {
+            FitSegment::Line(l) => (l.end - l.start).normalize(),
+            FitSegment::Arc(a) => {
+                let angle = if at_end { a.end_angle } else { a.start_angle };
+                let radial = Point2D::from_polar(1.0, angle);
+                let t = Point2D::new(-radial.y, radial.x);
+                if a.ccw {
+                    t
+                } else {
+                    -t
+                }
+            }
+        };
+        for pair in segments.windows(2) {
+            assert!(pair[0].end().approx_eq_eps(&pair[1].start(), 1e-9));
+            assert!(direction(&pair[0], true).approx_eq_eps(&direction(&pair[1], false), 1e-6));
+        }
+
+        let dense = flatten(&spline, tolerance / 100.0);
+        let polyline = to_arc_polyline(&spline, tolerance);
+        assert!(!polyline.closed);
+        for p in spline.to_polyline(500) {
+            let d = polyline.segments().iter().map(|s| segment_distance(s, p)).fold(f64::INFINITY, f64::min);
+            assert!(d <= tolerance * 1.01, "{d}");
+        }
+        assert!(polyline.vertices.len() < dense.len());
+
+        // A circle comes back as exact arcs
+        let circle = Circle2D::new(Point2D::origin(), 3.0);
+        let arcs = to_arc_polyline(&circle, 1e-6);
+        assert!(arcs.closed);
+        assert!((arcs.length() - TAU * 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_exact_nurbs() {
+        let arc = Arc2D::new(Point2D::new(1.0, 2.0), 5.0, 0.3, 2.9, false);
+        let nurbs = arc_to_nurbs(&arc);
+        assert!(nurbs.evaluate(0.0).approx_eq_eps(&arc.start_point(), 1e-9));
+        assert!(nurbs.evaluate(1.0).approx_eq_eps(&arc.end_point(), 1e-9));
+        for i in 0..=50 {
+            let p = nurbs.evaluate(i as f64 / 50.0);
+            assert!((p.distance_to(&arc.center) - 5.0).abs() < 1e-9);
+            assert!(arc.contains_angle(arc.center.angle_to(&p)));
+        }
+
+        let ellipse = Ellipse2D::new(Point2D::new(-1.0, 0.5), 4.0, 1.5, 0.7);
+        let nurbs = ellipse_to_nurbs(&ellipse);
+        assert_eq!(nurbs.control_points.len(), 9);
+        for i in 0..=40 {
+            // Back to the ellipse's own frame
+            let local = (nurbs.evaluate(i as f64 / 40.0) - ellipse.center).rotate(-0.7);
+            let level = (local.x / 4.0).powi(2) + (local.y / 1.5).powi(2);
+            assert!((level - 1.0).abs() < 1e-9);
+        }
+
+        let parabola = Parabola2D::new(Point2D::new(2.0, 1.0), 0.75, 0.4, -1.5, 2.5);
+        let hyperbola = Hyperbola2D::new(Point2D::new(-1.0, 3.0), 2.0, 0.5, 2.0, -2.0, 3.5);
+        for (nurbs, conic, start, end) in [
+            (
+                parabola_to_nurbs(&parabola),
+                Conic2D::from_parabola(&parabola),
+                parabola.start_point(),
+                parabola.end_point(),
+            ),
+            (
+                hyperbola_to_nurbs(&hyperbola),
+                Conic2D::from_hyperbola(&hyperbola),
+                hyperbola.start_point(),
+                hyperbola.end_point(),
+            ),
+        ] {
+            assert!(nurbs.evaluate(0.0).approx_eq_eps(&start, 1e-9));
+            assert!(nurbs.evaluate(1.0).approx_eq_eps(&end, 1e-9));
+            for i in 0..=40 {
+                let p = nurbs.evaluate(i as f64 / 40.0);
+                assert!(conic.value_at(p).abs() / conic.gradient_at(p).distance_to_origin() < 1e-9);
+            }
+        }
+        assert_eq!(hyperbola_to_nurbs(&hyperbola).control_points.len(), 7);
+
+        let bezier = BezierCurve::cubic(
+            Point2D::new(0.0, 0.0),
+            Point2D::new(1.0, 3.0),
+            Point2D::new(4.0, -1.0),
+            Point2D::new(5.0, 2.0),
+        );
+        let promoted = bezier.to_nurbs().unwrap();
+        for i in 0..=10 {
+            let t = i as f64 / 10.0;
+            assert!(promoted.evaluate(t).approx_eq_eps(&bezier.evaluate(t), 1e-9));
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! Curvature analysis of free-form curves
+//!
+//! Evaluates signed curvature along Bezier curves, B-splines and NURBS and
+//! finds their inflection points, the data behind curvature combs:
+//! - [`Curvature::curvature_at`] gives the signed curvature at a parameter;
+//!   positive bends left (counter-clockwise), negative bends right
+//! - [`Curvature::curvature_analysis`] samples every knot span evenly and
+//!   locates inflections, where the curvature changes sign
+//! - [`CurvatureAnalysis::comb`] turns the samples into comb teeth, drawn
+//!   along the normal on the convex side with length proportional to
+//!   curvature, and [`CurvatureAnalysis::envelope`] joins the tooth tips
+//!
+//! Sampling per knot span rather than per unit parameter keeps the comb
+//! dense where the curve has the most shape, and puts a sample on every
+//! knot, where curvature discontinuities show up.
+
+use crate::core::precision::EPSILON;
+use crate::geometry::curve::{BSpline, BezierCurve, NurbsCurve};
+use crate::geometry::point::Point2D;
+use serde::{Deserialize, Serialize};
+
+/// Curvatures below this fraction of the largest sampled curvature count as
+/// zero when looking for inflections, so straight runs don't report noise
+const FLAT_FRACTION: f64 = 1e-6;
+
+/// Bisection steps used to refine an inflection parameter
+const REFINE_STEPS: usize = 48;
+
+/// Curvature at a single parameter along a curve
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CurvatureSample {
+    /// Curve parameter
+    pub t: f64,
+    /// Point on the curve
+    pub point: Point2D,
+    /// Unit normal, the tangent turned a quarter counter-clockwise
+    pub normal: Point2D,
+    /// Signed curvature; positive bends towards the normal
+    pub curvature: f64,
+}
+
+impl CurvatureSample {
+    /// Radius of curvature, infinite where the curve is straight
+    pub fn radius(&self) -> f64 {
+        if self.curvature.abs() < EPSILON {
+            f64::INFINITY
+        } else {
+            1.0 / self.curvature.abs()
+        }
+    }
+
+    /// Tip of the comb tooth at this sample. Teeth point away from the
+    /// centre of curvature, `scale` world units per unit of curvature.
+    pub fn tooth(&self, scale: f64) -> Point2D {
+        self.point - self.normal * (self.curvature * scale)
+    }
+}
+
+/// Point where the curvature changes sign
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Inflection {
+    /// Curve parameter
+    pub t: f64,
+    /// Point on the curve
+    pub point: Point2D,
+}
+
+/// Sampled curvature along a curve
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct CurvatureAnalysis {
+    /// Samples in increasing parameter order
+    pub samples: Vec<CurvatureSample>,
+    /// Inflection points in increasing parameter order
+    pub inflections: Vec<Inflection>,
+}
+
+impl CurvatureAnalysis {
+    /// Largest absolute curvature sampled
+    pub fn max_curvature(&self) -> f64 {
+        self.samples.iter().map(|s| s.curvature.abs()).fold(0.0, f64::max)
+    }
+
+    /// Tightest radius of curvature sampled
+    pub fn min_radius(&self) -> f64 {
+        let max = self.max_curvature();
+        if max < EPSILON {
+            f64::INFINITY
+        } else {
+            1.0 / max
+        }
+    }
+
+    /// Comb scale that makes the longest tooth `length` world units long
+    pub fn comb_scale(&self, length: f64) -> f64 {
+        let max = self.max_curvature();
+        if max < EPSILON {
+            0.0
+        } else {
+            length / max
+        }
+    }
+
+    /// Comb teeth as (point on curve, tooth tip) pairs
+    pub fn comb(&self, scale: f64) -> Vec<(Point2D, Point2D)> {
+        self.samples.iter().map(|s| (s.point, s.tooth(scale))).collect()
+    }
+
+    /// Envelope through the tooth tips
+    pub fn envelope(&self, scale: f64) -> Vec<Point2D> {
+        self.samples.iter().map(|s| s.tooth(scale)).collect()
+    }
+}
+
+/// Curves with a second derivative, and so a curvature
+pub trait Curvature {
+    /// Distinct knot values bounding the polynomial pieces, including both
+    /// ends of the parameter range
+    fn pieces(&self) -> Vec<f64>;
+
+    /// Point, first derivative and second derivative at parameter t
+    fn derivatives(&self, t: f64) -> [Point2D; 3];
+
+    /// Signed curvature at parameter t; zero where the curve is degenerate
+    fn curvature_at(&self, t: f64) -> f64 {
+        let [_, d1, d2] = self.derivatives(t);
+        signed_curvature(d1, d2)
+    }
+
+    /// Sample the curvature `samples_per_span` times across every knot span
+    /// and locate the inflections between samples
+    fn curvature_analysis(&self, samples_per_span: usize) -> CurvatureAnalysis {
+        let steps = samples_per_span.max(1);
+        let pieces = self.pieces();
+
+        let mut samples = Vec::new();
+        for (i, span) in pieces.windows(2).enumerate() {
+            let first = if i == 0 { 0 } else { 1 };
+            for k in first..=steps {
+                let t = span[0] + (span[1] - span[0]) * k as f64 / steps as f64;
+                samples.push(sample(self, t));
+            }
+        }
+
+        let analysis = CurvatureAnalysis {
+            samples,
+            inflections: Vec::new(),
+        };
+        let flat = analysis.max_curvature() * FLAT_FRACTION;
+
+        // Compare each sample with the last one that wasn't flat, so an
+        // inflection through a straight run is still found once
+        let mut inflections = Vec::new();
+        let mut last: Option<&CurvatureSample> = None;
+        for s in &analysis.samples {
+            if s.curvature.abs() <= flat {
+                continue;
+            }
+            if let Some(prev) = last {
+                if prev.curvature.signum() != s.curvature.signum() {
+                    let t = refine(self, prev.t, s.t, prev.curvature);
+                    inflections.push(Inflection {
+                        t,
+                        point: self.derivatives(t)[0],
+                    });
+                }
+            }
+            last = Some(s);
+        }
+
+        CurvatureAnalysis {
+            inflections,
+            ..analysis
+        }
+    }
+}
+
+impl Curvature for BezierCurve {
+    fn pieces(&self) -> Vec<f64> {
+        vec![0.0, 1.0]
+    }
+
+    fn derivatives(&self, t: f64) -> [Point2D; 3] {
+        BezierCurve::derivatives(self, t)
+    }
+}
+
+impl Curvature for BSpline {
+    fn pieces(&self) -> Vec<f64> {
+        let (start, end) = self.parameter_range();
+        distinct_knots(&self.knots, start, end)
+    }
+
+    fn derivatives(&self, t: f64) -> [Point2D; 3] {
+        BSpline::derivatives(self, t)
+    }
+}
+
+impl Curvature for NurbsCurve {
+    fn pieces(&self) -> Vec<f64> {
+        let (start, end) = self.parameter_range();
+        distinct_knots(&self.knots, start, end)
+    }
+
+    fn derivatives(&self, t: f64) -> [Point2D; 3] {
+        NurbsCurve::derivatives(self, t)
+    }
+}
+
+/// Signed curvature from the first and second derivatives
+fn signed_curvature(d1: Point2D, d2: Point2D) -> f64 {
+    let speed = d1.distance_to_origin();
+    if speed < EPSILON {
+        0.0
+    } else {
+        d1.cross(&d2) / (speed * speed * speed)
+    }
+}
+
+fn sample<C: Curvature + ?Sized>(curve: &C, t: f64) -> CurvatureSample {
+    let [point, d1, d2] = curve.derivatives(t);
+    let tangent = d1.normalize();
+    CurvatureSample {
+        t,
+        point,
+        normal: Point2D::new(-tangent.y, tangent.x),
+        curvature: signed_curvature(d1, d2),
+    }
+}
+
+/// Bisect for the sign change of curvature between `lo` and `hi`, where the
+/// curvature at `lo` has the sign of `lo_curvature`
+fn refine<C: Curvature + ?Sized>(curve: &C, mut lo: f64, mut hi: f64, lo_curvature: f64) -> f64 {
+    let sign = lo_curvature.signum();
+    for _ in 0..REFINE_STEPS {
+        let mid = 0.5 * (lo + hi);
+        if curve.curvature_at(mid).signum() == sign {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    0.5 * (lo + hi)
+}
+
+fn distinct_knots(knots: &[f64], start: f64, end: f64) -> Vec<f64> {
+    let mut out = vec![start];
+    for &k in knots {
+        if k > start + EPSILON && k < end - EPSILON && k > out[out.len() - 1] + EPSILON {
+            out.push(k);
+        }
+    }
+    out.push(end);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::arc::Circle2D;
+    use crate::geometry::convert::circle_to_nurbs;
+
+    #[test]
+    fn test_circle_curvature() {
+        let circle = Circle2D::new(Point2D::new(1.0, 2.0), 4.0);
+        let nurbs = circle_to_nurbs(&circle);
+        let analysis = nurbs.curvature_analysis(8);
+
+        for s in &analysis.samples {
+            assert!(
+                (s.curvature - 0.25).abs() < 1e-9,
+                "curvature {} at {}",
+                s.curvature,
+                s.t
+            );
+            assert!((s.point.distance_to(&circle.center) - 4.0).abs() < 1e-9);
+            // Comb teeth point outwards
+            assert!(s.tooth(1.0).distance_to(&circle.center) > 4.0);
+        }
+        assert!(analysis.inflections.is_empty());
+        assert!((analysis.min_radius() - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_bspline_derivatives_match_finite_differences() {
+        let spline = BSpline::new(
+            vec![
+                Point2D::new(0.0, 0.0),
+                Point2D::new(1.0, 2.0),
+                Point2D::new(3.0, -1.0),
+                Point2D::new(4.0, 1.0),
+                Point2D::new(6.0, 0.0),
+            ],
+            vec![0.0, 0.0, 0.0, 0.0, 0.5, 1.0, 1.0, 1.0, 1.0],
+            3,
+        )
+        .unwrap();
+
+        let h = 1e-5;
+        for &t in &[0.1, 0.3, 0.6, 0.9] {
+            let [p, d1, d2] = spline.derivatives(t);
+            assert!(p.distance_to(&spline.evaluate(t)) < 1e-12);
+            let fd1 = (spline.evaluate(t + h) - spline.evaluate(t - h)) / (2.0 * h);
+            let fd2 = (spline.evaluate(t + h) - p * 2.0 + spline.evaluate(t - h)) / (h * h);
+            assert!(d1.distance_to(&fd1) < 1e-6);
+            assert!(d2.distance_to(&fd2) < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_s_curve_inflection() {
+        let curve = BezierCurve::new(vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(1.0, 1.0),
+            Point2D::new(2.0, -1.0),
+            Point2D::new(3.0, 0.0),
+        ]);
+        let analysis = curve.curvature_analysis(20);
+
+        assert_eq!(analysis.inflections.len(), 1);
+        let inflection = analysis.inflections[0];
+        // Symmetric about the midpoint
+        assert!((inflection.t - 0.5).abs() < 1e-9);
+        assert!(inflection.point.distance_to(&Point2D::new(1.5, 0.0)) < 1e-9);
+        assert!(curve.curvature_at(0.25) < 0.0);
+        assert!(curve.curvature_at(0.75) > 0.0);
+    }
+
+    #[test]
+    fn test_straight_line_has_flat_comb() {
+        let line = BezierCurve::new(vec![Point2D::new(0.0, 0.0), Point2D::new(2.0, 2.0)]);
+        let analysis = line.curvature_analysis(4);
+
+        assert_eq!(analysis.samples.len(), 5);
+        assert!(analysis.inflections.is_empty());
+        assert_eq!(analysis.comb_scale(1.0), 0.0);
+        assert!(analysis.min_radius().is_infinite());
+    }
+}
@@ -6,7 +6,7 @@
 use crate::core::*;
 use crate::core::precision::lerp;
 use crate::geometry::point::Point2D;
-use nalgebra::Point2 as NPoint2;
+use nalgebra::{DMatrix, Point2 as NPoint2};
 use serde::{Deserialize, Serialize};
 
 /// Bezier curve (quadratic or cubic)
@@ -63,6 +63,20 @@ impl BezierCurve {
         Some(BezierCurve::new(derivative_points))
     }
 
+    /// Point, first derivative and second derivative at parameter t
+    pub fn derivatives(&self, t: f64) -> [Point2D; 3] {
+        let first = hodograph(&self.control_points);
+        let second = hodograph(&first);
+        let at = |points: &[Point2D]| {
+            if points.is_empty() {
+                Point2D::origin()
+            } else {
+                de_casteljau(points, t)
+            }
+        };
+        [self.evaluate(t), at(&first), at(&second)]
+    }
+
     /// Get the tangent vector at parameter t
     pub fn tangent(&self, t: f64) -> Vector2 {
         if let Some(deriv) = self.derivative() {
@@ -128,6 +142,21 @@ impl BezierCurve {
         BezierCurve::new(new_points)
     }
 
+    /// The same curve as a clamped B-spline on [0, 1]
+    pub fn to_bspline(&self) -> Option<BSpline> {
+        if self.control_points.len() < 2 {
+            return None;
+        }
+        let degree = self.degree();
+        let knots = [vec![0.0; degree + 1], vec![1.0; degree + 1]].concat();
+        BSpline::new(self.control_points.clone(), knots, degree)
+    }
+
+    /// The same curve as a NURBS curve with unit weights on [0, 1]
+    pub fn to_nurbs(&self) -> Option<NurbsCurve> {
+        self.to_bspline().map(|spline| spline.to_nurbs())
+    }
+
     /// Find the closest point on the curve to a given point (approximate)
     pub fn closest_point(&self, point: &Point2D, samples: usize) -> (Point2D, f64) {
         let mut min_dist = f64::MAX;
@@ -149,6 +178,44 @@ impl BezierCurve {
     }
 }
 
+/// How fit points are spaced in parameter when a spline is interpolated
+/// through them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum KnotParameterization {
+    /// Equal steps regardless of spacing
+    Uniform,
+    /// Steps proportional to the distance between points (the DXF default)
+    #[default]
+    ChordLength,
+    /// Steps proportional to the square root of the distance; follows
+    /// sharp turns without looping
+    Centripetal,
+}
+
+/// Options for [`BSpline::interpolate`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SplineFit {
+    /// Degree; lowered when there are too few points for it
+    pub degree: usize,
+    /// Parameter spacing of the fit points
+    pub parameterization: KnotParameterization,
+    /// Direction the curve leaves the first point in
+    pub start_tangent: Option<Point2D>,
+    /// Direction the curve reaches the last point in
+    pub end_tangent: Option<Point2D>,
+}
+
+impl Default for SplineFit {
+    fn default() -> Self {
+        Self {
+            degree: 3,
+            parameterization: KnotParameterization::ChordLength,
+            start_tangent: None,
+            end_tangent: None,
+        }
+    }
+}
+
 /// B-spline curve
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BSpline {
@@ -216,6 +283,98 @@ impl BSpline {
         Self::new(control_points, knots, degree)
     }
 
+    /// Create a clamped B-spline passing through `fit_points`, as DXF
+    /// fit-point splines are defined
+    ///
+    /// The parameter runs from 0 to 1, with each fit point placed according
+    /// to `fit.parameterization` and knots averaged from those parameters.
+    /// End tangents are directions; each is scaled to the total chord length
+    /// and adds a control point. Repeated points are dropped. Returns `None`
+    /// for fewer than two distinct points.
+    pub fn interpolate(fit_points: &[Point2D], fit: &SplineFit) -> Option<Self> {
+        let mut points: Vec<Point2D> = Vec::with_capacity(fit_points.len());
+        for &p in fit_points {
+            if points.last().is_none_or(|q| q.distance_to(&p) > EPSILON) {
+                points.push(p);
+            }
+        }
+        if points.len() < 2 {
+            return None;
+        }
+        let n = points.len() - 1;
+        let params = fit_parameters(&points, fit.parameterization);
+        let chord: f64 = points.windows(2).map(|w| w[0].distance_to(&w[1])).sum();
+        let derivative = |tangent: Option<Point2D>| {
+            tangent
+                .filter(|t| t.distance_to_origin() > EPSILON)
+                .map(|t| t.normalize() * chord)
+        };
+        let (start, end) = (derivative(fit.start_tangent), derivative(fit.end_tangent));
+
+        // One parameter per control point, constrained ends counted twice
+        let mut averaged = Vec::with_capacity(n + 3);
+        if start.is_some() {
+            averaged.push(params[0]);
+        }
+        averaged.extend_from_slice(&params);
+        if end.is_some() {
+            averaged.push(params[n]);
+        }
+        let count = averaged.len();
+        let degree = fit.degree.clamp(1, count - 1);
+
+        let mut knots = vec![0.0; degree + 1];
+        for j in 1..count - degree {
+            knots.push(averaged[j..j + degree].iter().sum::<f64>() / degree as f64);
+        }
+        knots.extend(std::iter::repeat_n(1.0, degree + 1));
+
+        let basis = BSpline {
+            control_points: vec![Point2D::origin(); count],
+            knots,
+            degree,
+        };
+        let mut a = DMatrix::<f64>::zeros(count, count);
+        let mut b = DMatrix::<f64>::zeros(count, 2);
+        let mut row = 0;
+        let set_rhs = |b: &mut DMatrix<f64>, row: usize, v: Point2D| {
+            b[(row, 0)] = v.x;
+            b[(row, 1)] = v.y;
+        };
+        for (k, (&u, &q)) in params.iter().zip(&points).enumerate() {
+            if k == n {
+                if let Some(d) = end {
+                    let scale = (basis.knots[count + degree] - basis.knots[count - 1]) / degree as f64;
+                    a[(row, count - 2)] = -1.0;
+                    a[(row, count - 1)] = 1.0;
+                    set_rhs(&mut b, row, d * scale);
+                    row += 1;
+                }
+            }
+            let span = basis.find_knot_span(u);
+            for (i, value) in basis.basis_functions(span, u).into_iter().enumerate() {
+                a[(row, span - degree + i)] = value;
+            }
+            set_rhs(&mut b, row, q);
+            row += 1;
+            if k == 0 {
+                if let Some(d) = start {
+                    let scale = (basis.knots[degree + 1] - basis.knots[0]) / degree as f64;
+                    a[(row, 0)] = -1.0;
+                    a[(row, 1)] = 1.0;
+                    set_rhs(&mut b, row, d * scale);
+                    row += 1;
+                }
+            }
+        }
+
+        let solution = a.lu().solve(&b)?;
+        let control_points = (0..count)
+            .map(|i| Point2D::new(solution[(i, 0)], solution[(i, 1)]))
+            .collect();
+        Self::new(control_points, basis.knots, degree)
+    }
+
     /// Evaluate the B-spline at parameter t using Cox-de Boor algorithm
     pub fn evaluate(&self, t: f64) -> Point2D {
         let t = t.clamp(self.knots[0], self.knots[self.knots.len() - 1]);
@@ -241,8 +400,23 @@ impl BSpline {
         Point2D::new(x, y)
     }
 
+    /// Point, first derivative and second derivative at parameter t
+    pub fn derivatives(&self, t: f64) -> [Point2D; 3] {
+        let t = t.clamp(self.knots[0], self.knots[self.knots.len() - 1]);
+        let span = self.find_knot_span(t);
+        let ders = basis_derivatives(&self.knots, self.degree, span, t, 2);
+
+        let mut out = [Point2D::origin(); 3];
+        for (k, row) in ders.iter().enumerate() {
+            for (j, &b) in row.iter().enumerate() {
+                out[k] = out[k] + self.control_points[span - self.degree + j] * b;
+            }
+        }
+        out
+    }
+
     /// Find the knot span containing parameter t
-    fn find_knot_span(&self, t: f64) -> usize {
+    pub(crate) fn find_knot_span(&self, t: f64) -> usize {
         let n = self.control_points.len() - 1;
 
         // Special case: t at the end of knot vector
@@ -267,7 +441,7 @@ impl BSpline {
     }
 
     /// Compute basis functions using Cox-de Boor recursion
-    fn basis_functions(&self, span: usize, t: f64) -> Vec<f64> {
+    pub(crate) fn basis_functions(&self, span: usize, t: f64) -> Vec<f64> {
         let mut basis = vec![0.0; self.degree + 1];
         let mut left = vec![0.0; self.degree + 1];
         let mut right = vec![0.0; self.degree + 1];
@@ -307,6 +481,16 @@ impl BSpline {
             .collect()
     }
 
+    /// The same curve as a NURBS curve with unit weights
+    pub fn to_nurbs(&self) -> NurbsCurve {
+        NurbsCurve {
+            control_points: self.control_points.clone(),
+            weights: vec![1.0; self.control_points.len()],
+            knots: self.knots.clone(),
+            degree: self.degree,
+        }
+    }
+
     /// Get the bounding box
     pub fn bounding_box(&self) -> Option<BoundingBox2> {
         let points: Vec<NPoint2<f64>> = self
@@ -425,6 +609,34 @@ impl NurbsCurve {
         }
     }
 
+    /// Point, first derivative and second derivative at parameter t
+    ///
+    /// Differentiates the weighted (homogeneous) curve and applies the
+    /// quotient rule.
+    pub fn derivatives(&self, t: f64) -> [Point2D; 3] {
+        let t = t.clamp(self.knots[0], self.knots[self.knots.len() - 1]);
+        let span = self.find_knot_span(t);
+        let ders = basis_derivatives(&self.knots, self.degree, span, t, 2);
+
+        let mut a = [Point2D::origin(); 3];
+        let mut w = [0.0; 3];
+        for (k, row) in ders.iter().enumerate() {
+            for (j, &b) in row.iter().enumerate() {
+                let i = span - self.degree + j;
+                a[k] = a[k] + self.control_points[i] * (b * self.weights[i]);
+                w[k] += b * self.weights[i];
+            }
+        }
+        if w[0].abs() < EPSILON {
+            return [Point2D::origin(); 3];
+        }
+
+        let point = a[0] / w[0];
+        let first = (a[1] - point * w[1]) / w[0];
+        let second = (a[2] - first * (2.0 * w[1]) - point * w[2]) / w[0];
+        [point, first, second]
+    }
+
     /// Find the knot span containing parameter t
     fn find_knot_span(&self, t: f64) -> usize {
         let n = self.control_points.len() - 1;
@@ -512,6 +724,105 @@ impl NurbsCurve {
 
 // Helper functions
 
+/// Parameters in [0, 1] for interpolating through distinct points
+fn fit_parameters(points: &[Point2D], parameterization: KnotParameterization) -> Vec<f64> {
+    let steps: Vec<f64> = points
+        .windows(2)
+        .map(|w| {
+            let d = w[0].distance_to(&w[1]);
+            match parameterization {
+                KnotParameterization::Uniform => 1.0,
+                KnotParameterization::ChordLength => d,
+                KnotParameterization::Centripetal => d.sqrt(),
+            }
+        })
+        .collect();
+    let total: f64 = steps.iter().sum();
+    let mut params = Vec::with_capacity(points.len());
+    let mut running = 0.0;
+    params.push(0.0);
+    for step in &steps[..steps.len() - 1] {
+        running += step;
+        params.push(running / total);
+    }
+    params.push(1.0);
+    params
+}
+
+/// Control points of the derivative of a Bezier curve; empty for a
+/// single point
+fn hodograph(points: &[Point2D]) -> Vec<Point2D> {
+    let n = points.len().saturating_sub(1) as f64;
+    points.windows(2).map(|w| (w[1] - w[0]) * n).collect()
+}
+
+/// Non-zero basis functions in knot span `span` and their derivatives up
+/// to order `n` at `t` (The NURBS Book, algorithm A2.3). Row `k` holds the
+/// k-th derivatives of the basis functions of control points
+/// `span - degree ..= span`; orders above the degree are zero.
+fn basis_derivatives(knots: &[f64], degree: usize, span: usize, t: f64, n: usize) -> Vec<Vec<f64>> {
+    let p = degree;
+    let mut ndu = vec![vec![0.0; p + 1]; p + 1];
+    let mut left = vec![0.0; p + 1];
+    let mut right = vec![0.0; p + 1];
+    ndu[0][0] = 1.0;
+    for j in 1..=p {
+        left[j] = t - knots[span + 1 - j];
+        right[j] = knots[span + j] - t;
+        let mut saved = 0.0;
+        for r in 0..j {
+            // Lower triangle holds the knot differences
+            ndu[j][r] = right[r + 1] + left[j - r];
+            let temp = ndu[r][j - 1] / ndu[j][r];
+            ndu[r][j] = saved + right[r + 1] * temp;
+            saved = left[j - r] * temp;
+        }
+        ndu[j][j] = saved;
+    }
+
+    let mut ders = vec![vec![0.0; p + 1]; n + 1];
+    for j in 0..=p {
+        ders[0][j] = ndu[j][p];
+    }
+
+    let mut a = [vec![0.0; p + 1], vec![0.0; p + 1]];
+    for r in 0..=p {
+        let (mut s1, mut s2) = (0, 1);
+        a[0][0] = 1.0;
+        for k in 1..=n.min(p) {
+            let mut d = 0.0;
+            let pk = p - k;
+            if r >= k {
+                let rk = r - k;
+                a[s2][0] = a[s1][0] / ndu[pk + 1][rk];
+                d = a[s2][0] * ndu[rk][pk];
+            }
+            let j1 = if r + 1 >= k { 1 } else { k - r };
+            let j2 = if r <= pk + 1 { k - 1 } else { p - r };
+            for j in j1..=j2 {
+                let rkj = r + j - k;
+                a[s2][j] = (a[s1][j] - a[s1][j - 1]) / ndu[pk + 1][rkj];
+                d += a[s2][j] * ndu[rkj][pk];
+            }
+            if r <= pk {
+                a[s2][k] = -a[s1][k - 1] / ndu[pk + 1][r];
+                d += a[s2][k] * ndu[r][pk];
+            }
+            ders[k][r] = d;
+            std::mem::swap(&mut s1, &mut s2);
+        }
+    }
+
+    let mut factor = p as f64;
+    for (k, row) in ders.iter_mut().enumerate().take(n.min(p) + 1).skip(1) {
+        for value in row.iter_mut() {
+            *value *= factor;
+        }
+        factor *= (p - k) as f64;
+    }
+    ders
+}
+
 /// De Casteljau's algorithm for Bezier curve evaluation
 fn de_casteljau(points: &[Point2D], t: f64) -> Point2D {
     let mut temp = points.to_vec();
@@ -632,6 +943,70 @@ mod tests {
         assert!(end.approx_eq(&points[points.len() - 1]));
     }
 
+    #[test]
+    fn test_bspline_interpolate_fit_points() {
+        let points = vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(1.0, 2.0),
+            Point2D::new(3.0, 3.0),
+            Point2D::new(6.0, 1.0),
+            Point2D::new(7.0, 4.0),
+        ];
+        for parameterization in [
+            KnotParameterization::Uniform,
+            KnotParameterization::ChordLength,
+            KnotParameterization::Centripetal,
+        ] {
+            let fit = SplineFit {
+                parameterization,
+                ..Default::default()
+            };
+            let curve = BSpline::interpolate(&points, &fit).unwrap();
+            assert_eq!(curve.degree, 3);
+            assert_eq!(curve.control_points.len(), points.len());
+            for (u, p) in fit_parameters(&points, parameterization).into_iter().zip(&points) {
+                assert!(curve.evaluate(u).approx_eq_eps(p, 1e-9));
+            }
+        }
+
+        // Two points make a line; repeats are dropped
+        let line = BSpline::interpolate(&[points[0], points[0], points[1]], &SplineFit::default()).unwrap();
+        assert_eq!(line.degree, 1);
+        assert!(line.evaluate(0.5).approx_eq(&Point2D::new(0.5, 1.0)));
+        assert!(BSpline::interpolate(&[points[0], points[0]], &SplineFit::default()).is_none());
+    }
+
+    #[test]
+    fn test_bspline_interpolate_end_tangents() {
+        let points = vec![Point2D::new(0.0, 0.0), Point2D::new(4.0, 1.0), Point2D::new(8.0, 0.0)];
+        let fit = SplineFit {
+            start_tangent: Some(Point2D::new(0.0, 1.0)),
+            end_tangent: Some(Point2D::new(0.0, -2.0)),
+            ..Default::default()
+        };
+        let curve = BSpline::interpolate(&points, &fit).unwrap();
+        assert_eq!(curve.control_points.len(), 5);
+        assert!(curve.evaluate(0.0).approx_eq(&points[0]));
+        assert!(curve.evaluate(1.0).approx_eq(&points[2]));
+        assert!(curve.evaluate(0.5).approx_eq_eps(&points[1], 1e-9));
+
+        let h = 1e-6;
+        let leaving = (curve.evaluate(h) - curve.evaluate(0.0)).normalize();
+        let arriving = (curve.evaluate(1.0) - curve.evaluate(1.0 - h)).normalize();
+        assert!(leaving.approx_eq_eps(&Point2D::new(0.0, 1.0), 1e-4));
+        assert!(arriving.approx_eq_eps(&Point2D::new(0.0, -1.0), 1e-4));
+
+        // A tangent at one end only
+        let fit = SplineFit {
+            end_tangent: Some(Point2D::new(1.0, 0.0)),
+            ..Default::default()
+        };
+        let curve = BSpline::interpolate(&points, &fit).unwrap();
+        assert_eq!(curve.control_points.len(), 4);
+        let arriving = (curve.evaluate(1.0) - curve.evaluate(1.0 - h)).normalize();
+        assert!(arriving.approx_eq_eps(&Point2D::new(1.0, 0.0), 1e-4));
+    }
+
     #[test]
     fn test_nurbs_curve() {
         let points = vec![
@@ -0,0 +1,624 @@
+//! Quadric error metric mesh decimation
+//!
+//! [`Decimator`] reduces a triangle mesh by collapsing edges in order of
+//! the error they introduce, measured with Garland-Heckbert quadrics: each
+//! vertex accumulates the planes of the faces around it, and a collapse is
+//! charged the squared distance from the merged vertex to all of them.
+//! Collapses run from a priority queue, so meshes of tens of millions of
+//! triangles reduce in `O(n log n)`.
+//!
+//! A collapse is skipped if it would make the surface non-manifold or turn
+//! a face over, and open edges carry an extra quadric that keeps them in
+//! place, so holes and sheet outlines survive decimation.
+//!
+//! [`MeshLod`] keeps an imported mesh together with decimated levels for
+//! display; the original stays untouched for export.
+
+use crate::geometry::mesh::{TriangleFace, TriangleMesh, Vertex};
+use nalgebra::{Matrix3, Point3, Vector3};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Decimation settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decimator {
+    /// Stop once the mesh has this many faces or fewer
+    pub target_faces: usize,
+    /// Stop before a collapse costing more than this (squared distance)
+    pub max_error: f64,
+    /// Weight of the quadrics holding open edges in place
+    pub boundary_weight: f64,
+    /// Least cosine allowed between a face's normal before and after a
+    /// collapse
+    pub min_normal_dot: f64,
+}
+
+impl Default for Decimator {
+    fn default() -> Self {
+        Self {
+            target_faces: 0,
+            max_error: f64::INFINITY,
+            boundary_weight: 1000.0,
+            min_normal_dot: 0.2,
+        }
+    }
+}
+
+/// Result of a decimation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decimated {
+    /// Reduced mesh
+    pub mesh: TriangleMesh,
+    /// Edges collapsed
+    pub collapses: usize,
+    /// Largest collapse cost accepted
+    pub max_error: f64,
+}
+
+/// Symmetric 4x4 error quadric, upper triangle row by row
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// Squared distance to the plane `n . p + d = 0`, times `weight`
+    fn plane(n: Vector3<f64>, d: f64, weight: f64) -> Self {
+        let [a, b, c] = [n.x, n.y, n.z];
+        Self(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|q| q * weight),
+        )
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (q, o) in self.0.iter_mut().zip(other.0) {
+            *q += o;
+        }
+    }
+
+    fn sum(&self, other: &Quadric) -> Quadric {
+        let mut q = *self;
+        q.add(other);
+        q
+    }
+
+    fn error(&self, p: &Point3<f64>) -> f64 {
+        let [aa, ab, ac, ad, bb, bc, bd, cc, cd, dd] = self.0;
+        let (x, y, z) = (p.x, p.y, p.z);
+        aa * x * x
+            + 2.0 * ab * x * y
+            + 2.0 * ac * x * z
+            + 2.0 * ad * x
+            + bb * y * y
+            + 2.0 * bc * y * z
+            + 2.0 * bd * y
+            + cc * z * z
+            + 2.0 * cd * z
+            + dd
+    }
+
+    /// Point of least error, if the quadric pins one down
+    fn optimum(&self) -> Option<Point3<f64>> {
+        let [aa, ab, ac, ad, bb, bc, bd, cc, cd, _] = self.0;
+        let a = Matrix3::new(aa, ab, ac, ab, bb, bc, ac, bc, cc);
+        // Relative determinant test: flat or creased regions leave the
+        // system singular and the optimum far off along the free direction
+        let scale = aa.abs().max(bb.abs()).max(cc.abs());
+        if scale <= 0.0 || a.determinant().abs() < 1e-10 * scale * scale * scale {
+            return None;
+        }
+        a.try_inverse()
+            .map(|inv| Point3::from(inv * -Vector3::new(ad, bd, cd)))
+    }
+}
+
+/// Queued edge collapse
+struct Candidate {
+    cost: f64,
+    u: u32,
+    v: u32,
+    stamps: (u32, u32),
+    target: Point3<f64>,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    // Reversed so the max-heap pops the cheapest collapse
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+/// Each undirected edge once, with its lower vertex first, one of its faces
+/// and how many faces share it
+fn unique_edges(faces: &[[u32; 3]]) -> Vec<(u32, u32, u32, usize)> {
+    let mut edges: Vec<(u32, u32, u32)> = faces
+        .iter()
+        .enumerate()
+        .flat_map(|(i, f)| (0..3).map(move |k| (f[k], f[(k + 1) % 3], i as u32)))
+        .map(|(a, b, f)| (a.min(b), a.max(b), f))
+        .collect();
+    edges.sort_unstable();
+    let mut unique: Vec<(u32, u32, u32, usize)> = Vec::with_capacity(edges.len() / 2 + 1);
+    for (a, b, f) in edges {
+        match unique.last_mut() {
+            Some(last) if last.0 == a && last.1 == b => last.3 += 1,
+            _ => unique.push((a, b, f, 1)),
+        }
+    }
+    unique
+}
+
+/// Working state of a decimation
+struct Collapser {
+    positions: Vec<Point3<f64>>,
+    faces: Vec<[u32; 3]>,
+    face_alive: Vec<bool>,
+    vertex_faces: Vec<Vec<u32>>,
+    quadrics: Vec<Quadric>,
+    stamps: Vec<u32>,
+    vertex_alive: Vec<bool>,
+}
+
+impl Collapser {
+    fn new(mesh: &TriangleMesh, boundary_weight: f64) -> Self {
+        let positions: Vec<Point3<f64>> = mesh.vertices.iter().map(|v| v.position).collect();
+        let faces: Vec<[u32; 3]> = mesh
+            .faces
+            .iter()
+            .map(|f| f.vertices.map(|v| v as u32))
+            .collect();
+        let mut vertex_faces = vec![Vec::new(); positions.len()];
+        let mut quadrics = vec![Quadric::default(); positions.len()];
+
+        for (i, face) in faces.iter().enumerate() {
+            let [a, b, c] = face.map(|v| positions[v as usize]);
+            let cross = (b - a).cross(&(c - a));
+            let area = cross.norm() / 2.0;
+            if let Some(n) = cross.try_normalize(0.0) {
+                let q = Quadric::plane(n, -n.dot(&a.coords), area);
+                for &v in face {
+                    quadrics[v as usize].add(&q);
+                }
+            }
+            for &v in face {
+                vertex_faces[v as usize].push(i as u32);
+            }
+        }
+
+        // Open edges: a plane through the edge, perpendicular to its face
+        for (a, b, f, count) in unique_edges(&faces) {
+            if count != 1 {
+                continue;
+            }
+            let [p, q] = [a, b].map(|v| positions[v as usize]);
+            let face = faces[f as usize].map(|v| positions[v as usize]);
+            let normal = (face[1] - face[0]).cross(&(face[2] - face[0]));
+            let edge = q - p;
+            if let Some(n) = edge.cross(&normal).try_normalize(0.0) {
+                let plane =
+                    Quadric::plane(n, -n.dot(&p.coords), boundary_weight * edge.norm_squared());
+                quadrics[a as usize].add(&plane);
+                quadrics[b as usize].add(&plane);
+            }
+        }
+
+        Self {
+            face_alive: vec![true; faces.len()],
+            stamps: vec![0; positions.len()],
+            vertex_alive: vec![true; positions.len()],
+            positions,
+            faces,
+            vertex_faces,
+            quadrics,
+        }
+    }
+
+    fn candidate(&self, u: u32, v: u32) -> Candidate {
+        let q = self.quadrics[u as usize].sum(&self.quadrics[v as usize]);
+        let (pu, pv) = (self.positions[u as usize], self.positions[v as usize]);
+        let target = q.optimum().unwrap_or_else(|| {
+            [pu, pv, nalgebra::center(&pu, &pv)]
+                .into_iter()
+                .min_by(|a, b| q.error(a).total_cmp(&q.error(b)))
+                .unwrap_or(pu)
+        });
+        Candidate {
+            cost: q.error(&target).max(0.0),
+            u,
+            v,
+            stamps: (self.stamps[u as usize], self.stamps[v as usize]),
+            target,
+        }
+    }
+
+    fn live_faces(&self, v: u32) -> impl Iterator<Item = u32> + '_ {
+        self.vertex_faces[v as usize]
+            .iter()
+            .copied()
+            .filter(|&f| self.face_alive[f as usize])
+    }
+
+    fn neighbors(&self, v: u32) -> Vec<u32> {
+        let mut neighbors: Vec<u32> = self
+            .live_faces(v)
+            .flat_map(|f| self.faces[f as usize])
+            .filter(|&w| w != v)
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors
+    }
+
+    /// Whether collapsing `u`-`v` keeps the surface manifold and no face
+    /// turns over
+    fn can_collapse(&self, c: &Candidate, min_normal_dot: f64) -> bool {
+        let (u, v) = (c.u, c.v);
+        // Link condition: the only vertices adjacent to both are the apexes
+        // of the faces on the edge
+        let shared: Vec<u32> = self
+            .live_faces(u)
+            .filter(|&f| self.faces[f as usize].contains(&v))
+            .collect();
+        if shared.is_empty() {
+            return false;
+        }
+        let nu = self.neighbors(u);
+        let common = self
+            .neighbors(v)
+            .into_iter()
+            .filter(|w| nu.binary_search(w).is_ok())
+            .count();
+        if common != shared.len() {
+            return false;
+        }
+
+        for (moving, other) in [(u, v), (v, u)] {
+            for f in self.live_faces(moving) {
+                let face = self.faces[f as usize];
+                if face.contains(&other) {
+                    continue;
+                }
+                let before = face.map(|w| self.positions[w as usize]);
+                let after = face.map(|w| {
+                    if w == moving {
+                        c.target
+                    } else {
+                        self.positions[w as usize]
+                    }
+                });
+                let n0 = (before[1] - before[0]).cross(&(before[2] - before[0]));
+                let n1 = (after[1] - after[0]).cross(&(after[2] - after[0]));
+                match (n0.try_normalize(0.0), n1.try_normalize(0.0)) {
+                    (Some(n0), Some(n1)) if n0.dot(&n1) >= min_normal_dot => {}
+                    (None, Some(_)) => {}
+                    _ => return false,
+                }
+            }
+        }
+        true
+    }
+
+    /// Merge `v` into `u`; returns the number of faces removed
+    fn collapse(&mut self, c: &Candidate) -> usize {
+        let (u, v) = (c.u, c.v);
+        self.positions[u as usize] = c.target;
+        let qv = self.quadrics[v as usize];
+        self.quadrics[u as usize].add(&qv);
+
+        let mut removed = 0;
+        let moved = std::mem::take(&mut self.vertex_faces[v as usize]);
+        for f in moved {
+            if !self.face_alive[f as usize] {
+                continue;
+            }
+            let face = &mut self.faces[f as usize];
+            if face.contains(&u) {
+                self.face_alive[f as usize] = false;
+                removed += 1;
+            } else {
+                for w in face.iter_mut() {
+                    if *w == v {
+                        *w = u;
+                    }
+                }
+                self.vertex_faces[u as usize].push(f);
+            }
+        }
+        let alive = &self.face_alive;
+        self.vertex_faces[u as usize].retain(|&f| alive[f as usize]);
+        self.vertex_alive[v as usize] = false;
+        self.stamps[u as usize] += 1;
+        removed
+    }
+
+    fn into_mesh(self, original: &TriangleMesh) -> TriangleMesh {
+        let mut remap = vec![usize::MAX; self.positions.len()];
+        let mut vertices = Vec::new();
+        let mut faces = Vec::new();
+        for (face, _) in self
+            .faces
+            .iter()
+            .zip(&self.face_alive)
+            .filter(|(_, &alive)| alive)
+        {
+            let corners = face.map(|v| {
+                let v = v as usize;
+                if remap[v] == usize::MAX {
+                    remap[v] = vertices.len();
+                    vertices.push(Vertex {
+                        position: self.positions[v],
+                        normal: None,
+                        ..original.vertices[v]
+                    });
+                }
+                remap[v]
+            });
+            faces.push(TriangleFace { vertices: corners });
+        }
+        TriangleMesh::from_data(vertices, faces)
+    }
+}
+
+impl Decimator {
+    /// Decimate to at most `target_faces` faces
+    pub fn new(target_faces: usize) -> Self {
+        Self {
+            target_faces,
+            ..Self::default()
+        }
+    }
+
+    /// Reduce a mesh; the input is left unchanged
+    ///
+    /// Decimation stops at the target face count, at the error limit, or
+    /// when no remaining collapse is allowed. Vertex normals are dropped
+    /// since they no longer match the surface; texture coordinates are
+    /// kept from the surviving vertex.
+    pub fn decimate(&self, mesh: &TriangleMesh) -> Decimated {
+        let mut state = Collapser::new(mesh, self.boundary_weight);
+        let mut heap: BinaryHeap<Candidate> = unique_edges(&state.faces)
+            .into_iter()
+            .map(|(a, b, _, _)| state.candidate(a, b))
+            .collect();
+
+        let mut live = mesh.faces.len();
+        let mut collapses = 0;
+        let mut max_error: f64 = 0.0;
+        while live > self.target_faces {
+            let Some(c) = heap.pop() else {
+                break;
+            };
+            if c.cost > self.max_error {
+                break;
+            }
+            let (u, v) = (c.u as usize, c.v as usize);
+            if !state.vertex_alive[u]
+                || !state.vertex_alive[v]
+                || (state.stamps[u], state.stamps[v]) != c.stamps
+                || !state.can_collapse(&c, self.min_normal_dot)
+            {
+                continue;
+            }
+
+            live -= state.collapse(&c);
+            collapses += 1;
+            max_error = max_error.max(c.cost);
+            for w in state.neighbors(c.u) {
+                heap.push(state.candidate(c.u, w));
+            }
+        }
+
+        Decimated {
+            mesh: state.into_mesh(mesh),
+            collapses,
+            max_error,
+        }
+    }
+}
+
+/// An imported mesh with decimated levels of detail for display
+///
+/// The original is kept as imported, for export and measurement; each level
+/// is decimated from the one before it.
+#[derive(Debug, Clone)]
+pub struct MeshLod {
+    original: TriangleMesh,
+    levels: Vec<TriangleMesh>,
+}
+
+impl MeshLod {
+    /// Levels halving the face count until at most `min_faces` remain
+    pub fn new(original: TriangleMesh, min_faces: usize) -> Self {
+        let mut budgets = Vec::new();
+        let mut faces = original.faces.len() / 2;
+        while faces >= min_faces.max(1) {
+            budgets.push(faces);
+            faces /= 2;
+        }
+        Self::with_budgets(original, &budgets)
+    }
+
+    /// One level per face budget, largest first
+    pub fn with_budgets(original: TriangleMesh, budgets: &[usize]) -> Self {
+        let mut levels: Vec<TriangleMesh> = Vec::with_capacity(budgets.len());
+        for &budget in budgets {
+            let source = levels.last().unwrap_or(&original);
+            if source.faces.len() <= budget {
+                continue;
+            }
+            let level = Decimator::new(budget).decimate(source).mesh;
+            // Stop once decimation can make no more progress
+            if level.faces.len() >= source.faces.len() {
+                break;
+            }
+            levels.push(level);
+        }
+        Self { original, levels }
+    }
+
+    /// The mesh as imported
+    pub fn original(&self) -> &TriangleMesh {
+        &self.original
+    }
+
+    /// Decimated levels, finest first
+    pub fn levels(&self) -> &[TriangleMesh] {
+        &self.levels
+    }
+
+    /// Finest mesh within `max_faces`, or the coarsest level if none is
+    pub fn for_budget(&self, max_faces: usize) -> &TriangleMesh {
+        std::iter::once(&self.original)
+            .chain(&self.levels)
+            .find(|m| m.faces.len() <= max_faces)
+            .unwrap_or_else(|| self.levels.last().unwrap_or(&self.original))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::repair::MeshRepair;
+
+    /// Unit sphere from an octahedron subdivided `levels` times
+    fn sphere(levels: usize) -> TriangleMesh {
+        let mut mesh = TriangleMesh::new();
+        for p in [
+            [1.0, 0.0, 0.0],
+            [-1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, -1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, -1.0],
+        ] {
+            mesh.add_vertex(Vertex::new(Point3::new(p[0], p[1], p[2])));
+        }
+        for [a, b, c] in [
+            [0, 2, 4],
+            [2, 1, 4],
+            [1, 3, 4],
+            [3, 0, 4],
+            [2, 0, 5],
+            [1, 2, 5],
+            [3, 1, 5],
+            [0, 3, 5],
+        ] {
+            mesh.add_face(TriangleFace::new(a, b, c));
+        }
+        for _ in 0..levels {
+            mesh.subdivide();
+            mesh.merge_vertices(1e-9);
+        }
+        for v in &mut mesh.vertices {
+            v.position = Point3::from(v.position.coords.normalize());
+        }
+        mesh
+    }
+
+    /// Flat `n` x `n` grid of unit squares
+    fn grid(n: usize) -> TriangleMesh {
+        let mut mesh = TriangleMesh::new();
+        for j in 0..=n {
+            for i in 0..=n {
+                mesh.add_vertex(Vertex::new(Point3::new(i as f64, j as f64, 0.0)));
+            }
+        }
+        let at = |i: usize, j: usize| j * (n + 1) + i;
+        for j in 0..n {
+            for i in 0..n {
+                mesh.add_face(TriangleFace::new(at(i, j), at(i + 1, j), at(i + 1, j + 1)));
+                mesh.add_face(TriangleFace::new(at(i, j), at(i + 1, j + 1), at(i, j + 1)));
+            }
+        }
+        mesh
+    }
+
+    #[test]
+    fn test_decimate_sphere() {
+        let original = sphere(4);
+        assert_eq!(original.faces.len(), 2048);
+
+        let result = Decimator::new(200).decimate(&original);
+        let mesh = &result.mesh;
+        assert!(mesh.faces.len() <= 200);
+        assert!(mesh.faces.len() >= 190);
+        assert!(result.collapses > 0);
+        // Still a closed, consistently wound surface near the sphere
+        assert!(MeshRepair::new().analyze(mesh).is_watertight());
+        for v in &mesh.vertices {
+            assert!((v.position.coords.norm() - 1.0).abs() < 0.05);
+        }
+        // The input is untouched
+        assert_eq!(original.faces.len(), 2048);
+
+        // An error limit stops early
+        let limited = Decimator {
+            max_error: 1e-12,
+            ..Decimator::new(0)
+        }
+        .decimate(&original);
+        assert!(limited.mesh.faces.len() > 200);
+    }
+
+    #[test]
+    fn test_decimate_flat_grid_keeps_outline() {
+        let mesh = grid(16);
+        let result = Decimator::new(2).decimate(&mesh);
+        // A plane costs nothing to simplify, but its outline is held
+        assert!(result.max_error < 1e-9);
+        assert!(result.mesh.faces.len() <= 40, "{}", result.mesh.faces.len());
+        let area: f64 = (0..result.mesh.faces.len())
+            .map(|f| {
+                let [a, b, c] = result.mesh.faces[f]
+                    .vertices
+                    .map(|v| result.mesh.vertices[v].position);
+                (b - a).cross(&(c - a)).norm() / 2.0
+            })
+            .sum();
+        assert!((area - 256.0).abs() < 1e-6);
+        for corner in [[0.0, 0.0], [16.0, 0.0], [16.0, 16.0], [0.0, 16.0]] {
+            assert!(result
+                .mesh
+                .vertices
+                .iter()
+                .any(|v| (v.position.x - corner[0]).abs() < 1e-9
+                    && (v.position.y - corner[1]).abs() < 1e-9));
+        }
+    }
+
+    #[test]
+    fn test_mesh_lod() {
+        let lod = MeshLod::new(sphere(4), 100);
+        assert_eq!(lod.original().faces.len(), 2048);
+        assert!(!lod.levels().is_empty());
+        let counts: Vec<usize> = lod.levels().iter().map(|m| m.faces.len()).collect();
+        assert!(counts.windows(2).all(|w| w[0] > w[1]), "{:?}", counts);
+        assert_eq!(lod.for_budget(10_000).faces.len(), 2048);
+        assert!(lod.for_budget(600).faces.len() <= 600);
+        assert_eq!(lod.for_budget(1).faces.len(), *counts.last().unwrap());
+    }
+}
@@ -0,0 +1,719 @@
+//! Delaunay triangulation and Voronoi diagrams
+//!
+//! [`Triangulation`] builds a Delaunay triangulation incrementally
+//! (Bowyer-Watson inside a large enclosing triangle), then inserts
+//! constraint edges by re-triangulating the triangles each one crosses, so
+//! the result is the constrained Delaunay triangulation: every constraint
+//! is an edge and every other edge is as Delaunay as the constraints allow.
+//! Boundaries and holes are handled by keeping the triangles inside an odd
+//! number of constraint loops.
+//!
+//! Voronoi cells are extracted from the triangulation as its dual, clipped
+//! to a bounding box so the cells of hull vertices are finite.
+
+use crate::core::precision::{self, EPSILON};
+use crate::core::primitives::BoundingBox2;
+use crate::geometry::point::Point2D;
+use crate::geometry::polygon::Polygon2D;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Missing triangle or neighbour
+const NONE: usize = usize::MAX;
+
+/// Vertices of the enclosing triangle, ahead of the input points
+const SUPER: usize = 3;
+
+/// Triangulation of a point set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Triangulation {
+    /// Input points; repeated points are used once, by their first index
+    pub vertices: Vec<Point2D>,
+    /// Triangles as indices into `vertices`, counter-clockwise
+    pub triangles: Vec<[usize; 3]>,
+    /// Constraint edges, split at input points lying on them
+    pub constraints: Vec<[usize; 2]>,
+}
+
+/// Voronoi cell of one triangulation vertex
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoronoiCell {
+    /// Index of the site in [`Triangulation::vertices`]
+    pub site: usize,
+    /// Cell polygon, counter-clockwise, clipped to the requested bounds
+    pub vertices: Vec<Point2D>,
+    /// Whether the cell is finite without clipping; false for sites on the
+    /// boundary of the triangulation
+    pub bounded: bool,
+}
+
+impl Triangulation {
+    /// Delaunay triangulation of `points`, covering their convex hull
+    ///
+    /// Returns `None` unless there are three points that are not collinear.
+    pub fn delaunay(points: &[Point2D]) -> Option<Self> {
+        Self::constrained(points, &[])
+    }
+
+    /// Constrained Delaunay triangulation covering the convex hull
+    ///
+    /// `edges` are index pairs into `points` that must appear as edges,
+    /// like terrain breaklines. Returns `None` for fewer than three
+    /// non-collinear points or if two constraint edges cross.
+    pub fn constrained(points: &[Point2D], edges: &[[usize; 2]]) -> Option<Self> {
+        let mut builder = Builder::build(points, edges)?;
+        let inside: Vec<bool> = builder.tris.iter().map(|tri| tri.iter().all(|&v| v >= SUPER)).collect();
+        builder.keep(&inside);
+        Some(builder.finish(points))
+    }
+
+    /// Constrained Delaunay triangulation of the regions enclosed by
+    /// `edges`
+    ///
+    /// The constraint edges must form closed loops; a triangle is kept when
+    /// it lies inside an odd number of them, so a loop inside another one
+    /// cuts a hole. Returns `None` in the same cases as [`constrained`].
+    ///
+    /// [`constrained`]: Self::constrained
+    pub fn bounded(points: &[Point2D], edges: &[[usize; 2]]) -> Option<Self> {
+        let mut builder = Builder::build(points, edges)?;
+        let inside: Vec<bool> = builder.depths().iter().map(|&depth| depth != NONE && depth % 2 == 1).collect();
+        builder.keep(&inside);
+        Some(builder.finish(points))
+    }
+
+    /// Constrained Delaunay triangulation of a polygon and its holes
+    pub fn from_polygon(polygon: &Polygon2D) -> Option<Self> {
+        let mut points = Vec::new();
+        let mut edges = Vec::new();
+        for ring in std::iter::once(&polygon.vertices).chain(&polygon.holes) {
+            let start = points.len();
+            points.extend_from_slice(ring);
+            edges.extend((0..ring.len()).map(|i| [start + i, start + (i + 1) % ring.len()]));
+        }
+        Self::bounded(&points, &edges)
+    }
+
+    /// Total area of the triangles
+    pub fn area(&self) -> f64 {
+        self.triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                let (a, b, c) = (self.vertices[a], self.vertices[b], self.vertices[c]);
+                (b - a).cross(&(c - a)) / 2.0
+            })
+            .sum()
+    }
+
+    /// Distinct edges, each with its smaller index first
+    pub fn edges(&self) -> Vec<[usize; 2]> {
+        let mut edges: Vec<[usize; 2]> = self
+            .triangles
+            .iter()
+            .flat_map(|&[a, b, c]| [[a, b], [b, c], [c, a]])
+            .map(|[a, b]| [a.min(b), a.max(b)])
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+        edges
+    }
+
+    /// Circumcentre of each triangle; the vertices of the Voronoi diagram
+    pub fn circumcenters(&self) -> Vec<Point2D> {
+        self.triangles
+            .iter()
+            .map(|&[a, b, c]| circumcenter(self.vertices[a], self.vertices[b], self.vertices[c]))
+            .collect()
+    }
+
+    /// Voronoi cells of the vertices, clipped to `bounds`
+    ///
+    /// Each cell is the part of `bounds` closer to its site than to any
+    /// vertex it shares an edge with. For a Delaunay triangulation these
+    /// are exactly the Voronoi cells; for a constrained one they follow the
+    /// constraints instead.
+    pub fn voronoi(&self, bounds: &BoundingBox2) -> Vec<VoronoiCell> {
+        let mut neighbours: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut directed = HashSet::new();
+        for &[a, b, c] in &self.triangles {
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                neighbours.entry(u).or_default().push(v);
+                directed.insert((u, v));
+            }
+        }
+        // An edge without its reverse lies on the boundary
+        let on_boundary: HashSet<usize> = directed
+            .iter()
+            .filter(|&&(u, v)| !directed.contains(&(v, u)))
+            .flat_map(|&(u, v)| [u, v])
+            .collect();
+
+        let (min, max) = (bounds.min, bounds.max);
+        let frame = vec![
+            Point2D::new(min.x, min.y),
+            Point2D::new(max.x, min.y),
+            Point2D::new(max.x, max.y),
+            Point2D::new(min.x, max.y),
+        ];
+        let mut sites: Vec<usize> = neighbours.keys().copied().collect();
+        sites.sort_unstable();
+        sites
+            .into_iter()
+            .map(|site| {
+                let s = self.vertices[site];
+                let cell = neighbours[&site].iter().fold(frame.clone(), |cell, &n| {
+                    let n = self.vertices[n];
+                    clip_half_plane(&cell, (s + n) / 2.0, n - s)
+                });
+                VoronoiCell {
+                    site,
+                    vertices: cell,
+                    bounded: !on_boundary.contains(&site),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Keep the part of `polygon` on the side of the line through `point`
+/// that `normal` points away from
+fn clip_half_plane(polygon: &[Point2D], point: Point2D, normal: Point2D) -> Vec<Point2D> {
+    let side = |p: Point2D| (p - point).dot(&normal);
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+    for (i, &p) in polygon.iter().enumerate() {
+        let q = polygon[(i + 1) % polygon.len()];
+        let (sp, sq) = (side(p), side(q));
+        if sp <= 0.0 {
+            clipped.push(p);
+        }
+        if (sp < 0.0 && sq > 0.0) || (sp > 0.0 && sq < 0.0) {
+            clipped.push(p + (q - p) * (sp / (sp - sq)));
+        }
+    }
+    clipped
+}
+
+fn circumcenter(a: Point2D, b: Point2D, c: Point2D) -> Point2D {
+    let (ab, ac) = (b - a, c - a);
+    let d = 2.0 * ab.cross(&ac);
+    let (ab2, ac2) = (ab.dot(&ab), ac.dot(&ac));
+    a + Point2D::new(ac.y * ab2 - ab.y * ac2, ab.x * ac2 - ac.x * ab2) / d
+}
+
+/// Exact-sign orientation, so near-collinear points never flip the walk or
+/// the cavity test
+fn orient(a: Point2D, b: Point2D, c: Point2D) -> f64 {
+    precision::orient2d([a.x, a.y], [b.x, b.y], [c.x, c.y])
+}
+
+/// Positive when `d` is inside the circle through counter-clockwise `a`,
+/// `b`, `c`; exact sign, so near-cocircular points are decided consistently
+fn incircle(a: Point2D, b: Point2D, c: Point2D, d: Point2D) -> f64 {
+    precision::incircle([a.x, a.y], [b.x, b.y], [c.x, c.y], [d.x, d.y])
+}
+
+/// Triangle mesh under construction; edge `i` of a triangle runs from its
+/// vertex `i` to vertex `i + 1`, and `adj[t][i]` is the triangle across it
+struct Builder {
+    points: Vec<Point2D>,
+    tris: Vec<[usize; 3]>,
+    adj: Vec<[usize; 3]>,
+    alive: Vec<bool>,
+    free: Vec<usize>,
+    vertex_tri: Vec<usize>,
+    fixed: HashSet<(usize, usize)>,
+    /// Internal vertex of each input point
+    index: Vec<usize>,
+    last: usize,
+    tolerance: f64,
+}
+
+impl Builder {
+    fn build(input: &[Point2D], edges: &[[usize; 2]]) -> Option<Self> {
+        let (mut min, mut max) = (Point2D::new(f64::INFINITY, f64::INFINITY), Point2D::new(f64::NEG_INFINITY, f64::NEG_INFINITY));
+        for p in input {
+            min = Point2D::new(min.x.min(p.x), min.y.min(p.y));
+            max = Point2D::new(max.x.max(p.x), max.y.max(p.y));
+        }
+        let size = (max.x - min.x).max(max.y - min.y);
+        if input.len() < 3 || !size.is_finite() || size <= 0.0 {
+            return None;
+        }
+        let scale = size.max(min.x.abs()).max(min.y.abs()).max(max.x.abs()).max(max.y.abs());
+
+        // Enclosing triangle far enough out not to disturb the hull
+        let center = (min + max) / 2.0;
+        let radius = size * 1e3;
+        let points: Vec<Point2D> = [90.0_f64, 210.0, 330.0]
+            .iter()
+            .map(|deg| center + Point2D::from_polar(radius, deg.to_radians()))
+            .collect();
+        let mut builder = Self {
+            points,
+            tris: vec![[0, 1, 2]],
+            adj: vec![[NONE; 3]],
+            alive: vec![true],
+            free: Vec::new(),
+            vertex_tri: vec![0; SUPER],
+            fixed: HashSet::new(),
+            index: vec![NONE; input.len()],
+            last: 0,
+            tolerance: EPSILON * scale,
+        };
+
+        // Insert in x order so each point is found close to the last one
+        let mut order: Vec<usize> = (0..input.len()).collect();
+        order.sort_by(|&i, &j| input[i].x.total_cmp(&input[j].x).then(input[i].y.total_cmp(&input[j].y)));
+        for i in order {
+            builder.index[i] = builder.insert_point(input[i]);
+        }
+        if builder.points.len() < SUPER + 3 || builder.tris_alive().all(|t| builder.tris[t].iter().any(|&v| v < SUPER)) {
+            return None;
+        }
+
+        for &[a, b] in edges {
+            let (a, b) = (*builder.index.get(a)?, *builder.index.get(b)?);
+            builder.insert_edge(a, b)?;
+        }
+        Some(builder)
+    }
+
+    fn tris_alive(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.tris.len()).filter(|&t| self.alive[t])
+    }
+
+    /// Remove the triangles not marked in `keep`
+    fn keep(&mut self, keep: &[bool]) {
+        for (alive, &keep) in self.alive.iter_mut().zip(keep) {
+            *alive &= keep;
+        }
+    }
+
+    /// Number of constraint edges crossed on the way to each triangle from
+    /// the outside
+    fn depths(&self) -> Vec<usize> {
+        let mut depth = vec![NONE; self.tris.len()];
+        let mut queue: VecDeque<usize> = self.tris_alive().filter(|&t| self.tris[t].iter().any(|&v| v < SUPER)).collect();
+        for &t in &queue {
+            depth[t] = 0;
+        }
+        // Breadth first with crossings queued behind, so every triangle is
+        // reached by its fewest crossings
+        let mut next = VecDeque::new();
+        while !queue.is_empty() {
+            while let Some(t) = queue.pop_front() {
+                for i in 0..3 {
+                    let n = self.adj[t][i];
+                    if n == NONE || depth[n] != NONE {
+                        continue;
+                    }
+                    let [a, b] = [self.tris[t][i], self.tris[t][(i + 1) % 3]];
+                    if self.fixed.contains(&edge_key(a, b)) {
+                        next.push_back((n, depth[t] + 1));
+                    } else {
+                        depth[n] = depth[t];
+                        queue.push_back(n);
+                    }
+                }
+            }
+            for (n, d) in next.drain(..) {
+                if depth[n] == NONE {
+                    depth[n] = d;
+                    queue.push_back(n);
+                }
+            }
+        }
+        depth
+    }
+
+    fn finish(self, input: &[Point2D]) -> Triangulation {
+        // First input index of each internal vertex
+        let mut origin = vec![NONE; self.points.len()];
+        for (i, &v) in self.index.iter().enumerate() {
+            if origin[v] == NONE {
+                origin[v] = i;
+            }
+        }
+        let triangles = self.tris_alive().map(|t| self.tris[t].map(|v| origin[v])).collect();
+        let mut constraints: Vec<[usize; 2]> = self
+            .fixed
+            .iter()
+            .map(|&(a, b)| {
+                let (a, b) = (origin[a], origin[b]);
+                [a.min(b), a.max(b)]
+            })
+            .collect();
+        constraints.sort_unstable();
+        Triangulation {
+            vertices: input.to_vec(),
+            triangles,
+            constraints,
+        }
+    }
+
+    /// Triangle containing `p`, walking from the last one touched
+    fn locate(&self, p: Point2D) -> usize {
+        let mut t = self.last;
+        'walk: for _ in 0..self.tris.len() * 2 {
+            for i in 0..3 {
+                let (a, b) = (self.points[self.tris[t][i]], self.points[self.tris[t][(i + 1) % 3]]);
+                if orient(a, b, p) < 0.0 && self.adj[t][i] != NONE {
+                    t = self.adj[t][i];
+                    continue 'walk;
+                }
+            }
+            return t;
+        }
+        // The walk can cycle on near-degenerate input
+        self.tris_alive()
+            .find(|&t| (0..3).all(|i| orient(self.points[self.tris[t][i]], self.points[self.tris[t][(i + 1) % 3]], p) >= 0.0))
+            .unwrap_or(self.last)
+    }
+
+    /// Insert a point, returning its vertex; points within tolerance of an
+    /// existing vertex return that vertex
+    fn insert_point(&mut self, p: Point2D) -> usize {
+        let start = self.locate(p);
+        if let Some(&v) = self.tris[start].iter().find(|&&v| self.points[v].distance_to(&p) <= self.tolerance) {
+            return v;
+        }
+        let mut cavity = vec![start];
+        let mut in_cavity: HashSet<usize> = cavity.iter().copied().collect();
+        let mut stack = vec![start];
+        loop {
+            while let Some(t) = stack.pop() {
+                for i in 0..3 {
+                    let n = self.adj[t][i];
+                    if n == NONE || in_cavity.contains(&n) {
+                        continue;
+                    }
+                    let [a, b, c] = self.tris[n].map(|v| self.points[v]);
+                    if incircle(a, b, c, p) > 0.0 {
+                        cavity.push(n);
+                        in_cavity.insert(n);
+                        stack.push(n);
+                    }
+                }
+            }
+            // Every boundary edge must see `p` on its left, or the new
+            // triangle would be flat or inverted
+            let flat = cavity.iter().flat_map(|&t| (0..3).map(move |i| (t, i))).find(|&(t, i)| {
+                let n = self.adj[t][i];
+                let (a, b) = (self.points[self.tris[t][i]], self.points[self.tris[t][(i + 1) % 3]]);
+                n != NONE && !in_cavity.contains(&n) && orient(a, b, p) <= 0.0
+            });
+            match flat {
+                Some((t, i)) => {
+                    let n = self.adj[t][i];
+                    cavity.push(n);
+                    in_cavity.insert(n);
+                    stack.push(n);
+                }
+                None => break,
+            }
+        }
+
+        for &t in &cavity {
+            for &v in &self.tris[t] {
+                if self.points[v].distance_to(&p) <= self.tolerance {
+                    return v;
+                }
+            }
+        }
+
+        let v = self.points.len();
+        self.points.push(p);
+        self.vertex_tri.push(NONE);
+        let new: Vec<[usize; 3]> = cavity
+            .iter()
+            .flat_map(|&t| (0..3).map(move |i| (t, i)))
+            .filter(|&(t, i)| !in_cavity.contains(&self.adj[t][i]))
+            .map(|(t, i)| [self.tris[t][i], self.tris[t][(i + 1) % 3], v])
+            .collect();
+        self.replace(&cavity, &new);
+        v
+    }
+
+    /// Insert a constraint edge, splitting it at vertices lying on it
+    fn insert_edge(&mut self, mut a: usize, b: usize) -> Option<()> {
+        while a != b {
+            a = self.insert_edge_part(a, b)?;
+        }
+        Some(())
+    }
+
+    /// Insert the part of edge `a`–`b` up to the first vertex on it,
+    /// returning that vertex
+    fn insert_edge_part(&mut self, a: usize, b: usize) -> Option<usize> {
+        let (pa, pb) = (self.points[a], self.points[b]);
+        let (length, tolerance) = (pa.distance_to(&pb), self.tolerance);
+        let on_line = |p: Point2D| (orient(pa, pb, p) / length).abs() <= tolerance && (p - pa).dot(&(pb - pa)) > 0.0;
+
+        // Find the triangle around `a` that the edge leaves through
+        let first = self.vertex_tri[a];
+        let mut t = first;
+        let (mut right, mut left) = (NONE, NONE);
+        for _ in 0..self.tris.len() {
+            let i = self.tris[t].iter().position(|&v| v == a)?;
+            let (c, d) = (self.tris[t][(i + 1) % 3], self.tris[t][(i + 2) % 3]);
+            for v in [c, d] {
+                if v == b || on_line(self.points[v]) {
+                    self.fixed.insert(edge_key(a, v));
+                    return Some(v);
+                }
+            }
+            if orient(pa, pb, self.points[c]) < 0.0 && orient(pa, pb, self.points[d]) > 0.0 {
+                (right, left) = (c, d);
+                break;
+            }
+            t = self.adj[t][(i + 2) % 3];
+            if t == NONE || t == first {
+                return None;
+            }
+        }
+        if right == NONE {
+            return None;
+        }
+
+        // Walk along the edge collecting the triangles it crosses and the
+        // vertices on either side
+        let mut cavity = vec![t];
+        let (mut right_chain, mut left_chain) = (vec![right], vec![left]);
+        let end = loop {
+            if self.fixed.contains(&edge_key(right, left)) {
+                return None;
+            }
+            let slot = (0..3).find(|&k| self.tris[t][k] == right && self.tris[t][(k + 1) % 3] == left)?;
+            t = self.adj[t][slot];
+            if t == NONE {
+                return None;
+            }
+            cavity.push(t);
+            let e = *self.tris[t].iter().find(|&&v| v != right && v != left)?;
+            if e == b || on_line(self.points[e]) {
+                break e;
+            }
+            if orient(pa, pb, self.points[e]) > 0.0 {
+                left_chain.push(e);
+                left = e;
+            } else {
+                right_chain.push(e);
+                right = e;
+            }
+        };
+
+        let mut new = Vec::new();
+        self.fill(a, end, &right_chain, &mut new);
+        left_chain.reverse();
+        self.fill(end, a, &left_chain, &mut new);
+        self.replace(&cavity, &new);
+        self.fixed.insert(edge_key(a, end));
+        Some(end)
+    }
+
+    /// Delaunay triangulation of the polygon `first`, `chain`..., `last`
+    /// (counter-clockwise) whose closing edge `last`–`first` is given
+    fn fill(&self, first: usize, last: usize, chain: &[usize], out: &mut Vec<[usize; 3]>) {
+        if chain.is_empty() {
+            return;
+        }
+        let (pf, pl) = (self.points[first], self.points[last]);
+        let mut pick = 0;
+        for (k, &v) in chain.iter().enumerate().skip(1) {
+            if incircle(pf, self.points[chain[pick]], pl, self.points[v]) > 0.0 {
+                pick = k;
+            }
+        }
+        let c = chain[pick];
+        out.push([first, c, last]);
+        self.fill(first, c, &chain[..pick], out);
+        self.fill(c, last, &chain[pick + 1..], out);
+    }
+
+    /// Replace the `cavity` triangles with `new` ones covering the same
+    /// region, relinking neighbours
+    fn replace(&mut self, cavity: &[usize], new: &[[usize; 3]]) {
+        let in_cavity: HashSet<usize> = cavity.iter().copied().collect();
+        let mut outside = HashMap::new();
+        for &t in cavity {
+            for i in 0..3 {
+                let n = self.adj[t][i];
+                if !in_cavity.contains(&n) {
+                    outside.insert((self.tris[t][i], self.tris[t][(i + 1) % 3]), n);
+                }
+            }
+            self.alive[t] = false;
+            self.free.push(t);
+        }
+
+        let mut edges: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+        for &tri in new {
+            let t = match self.free.pop() {
+                Some(t) => {
+                    self.tris[t] = tri;
+                    self.adj[t] = [NONE; 3];
+                    self.alive[t] = true;
+                    t
+                }
+                None => {
+                    self.tris.push(tri);
+                    self.adj.push([NONE; 3]);
+                    self.alive.push(true);
+                    self.tris.len() - 1
+                }
+            };
+            for (i, &v) in tri.iter().enumerate() {
+                self.vertex_tri[v] = t;
+                let (a, b) = (v, tri[(i + 1) % 3]);
+                if let Some(&(u, j)) = edges.get(&(b, a)) {
+                    self.adj[t][i] = u;
+                    self.adj[u][j] = t;
+                } else if let Some(&n) = outside.get(&(a, b)) {
+                    self.adj[t][i] = n;
+                    if n != NONE {
+                        if let Some(j) = (0..3).find(|&j| self.tris[n][j] == b && self.tris[n][(j + 1) % 3] == a) {
+                            self.adj[n][j] = t;
+                        }
+                    }
+                }
+                edges.insert((a, b), (t, i));
+            }
+            self.last = t;
+        }
+    }
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Point2 as NPoint2;
+
+    fn scattered(n: usize) -> Vec<Point2D> {
+        // Deterministic, well spread points
+        (0..n)
+            .map(|i| {
+                let t = i as f64;
+                Point2D::new((t * 0.618_033_988_7).fract() * 10.0, (t * 0.754_877_666_2).fract() * 10.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_delaunay_empty_circumcircles() {
+        let points = scattered(60);
+        let tri = Triangulation::delaunay(&points).unwrap();
+
+        let hull = crate::geometry::hull::convex_hull_2d(&points);
+        assert!((tri.area() - Polygon2D::new(hull).area()).abs() < 1e-9);
+        let used: HashSet<usize> = tri.triangles.iter().flatten().copied().collect();
+        assert_eq!(used.len(), points.len());
+        for &[a, b, c] in &tri.triangles {
+            let (a, b, c) = (points[a], points[b], points[c]);
+            assert!(orient(a, b, c) > 0.0);
+            for &p in &points {
+                assert!(incircle(a, b, c, p) <= 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_delaunay_degenerate() {
+        let line: Vec<Point2D> = (0..5).map(|i| Point2D::new(i as f64, i as f64)).collect();
+        assert!(Triangulation::delaunay(&line).is_none());
+        assert!(Triangulation::delaunay(&line[..2]).is_none());
+
+        // Repeated points are used once
+        let points = vec![Point2D::new(0.0, 0.0), Point2D::new(1.0, 0.0), Point2D::new(0.0, 1.0), Point2D::new(1.0, 0.0)];
+        let tri = Triangulation::delaunay(&points).unwrap();
+        assert_eq!(tri.triangles.len(), 1);
+        assert!(!tri.triangles[0].contains(&3));
+    }
+
+    #[test]
+    fn test_constraint_edge_is_kept() {
+        // The Delaunay edge would be the short vertical one
+        let points = vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(4.0, -1.0),
+            Point2D::new(8.0, 0.0),
+            Point2D::new(4.0, 1.0),
+        ];
+        assert!(!Triangulation::delaunay(&points).unwrap().edges().contains(&[0, 2]));
+
+        let tri = Triangulation::constrained(&points, &[[0, 2]]).unwrap();
+        assert!(tri.edges().contains(&[0, 2]));
+        assert_eq!(tri.triangles.len(), 2);
+        assert!((tri.area() - 8.0).abs() < 1e-12);
+
+        // A point on the constraint splits it
+        let mut points = points;
+        points.push(Point2D::new(4.0, 0.0));
+        let tri = Triangulation::constrained(&points, &[[0, 2]]).unwrap();
+        assert_eq!(tri.constraints, vec![[0, 4], [2, 4]]);
+
+        // Crossing constraints are rejected
+        assert!(Triangulation::constrained(&points[..4], &[[0, 2], [1, 3]]).is_none());
+    }
+
+    #[test]
+    fn test_polygon_with_hole() {
+        let mut polygon = Polygon2D::rectangle(Point2D::new(0.0, 0.0), Point2D::new(10.0, 10.0));
+        polygon.add_hole(vec![
+            Point2D::new(4.0, 4.0),
+            Point2D::new(4.0, 6.0),
+            Point2D::new(6.0, 6.0),
+            Point2D::new(6.0, 4.0),
+        ]);
+        let tri = Triangulation::from_polygon(&polygon).unwrap();
+        assert!((tri.area() - 96.0).abs() < 1e-9);
+        assert_eq!(tri.constraints.len(), 8);
+        for &[a, b, c] in &tri.triangles {
+            let centroid = (tri.vertices[a] + tri.vertices[b] + tri.vertices[c]) / 3.0;
+            assert!(polygon.contains_point(&centroid));
+        }
+
+        // An L-shaped outline keeps its notch empty
+        let outline = vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(4.0, 0.0),
+            Point2D::new(4.0, 1.0),
+            Point2D::new(1.0, 1.0),
+            Point2D::new(1.0, 4.0),
+            Point2D::new(0.0, 4.0),
+        ];
+        let tri = Triangulation::from_polygon(&Polygon2D::new(outline)).unwrap();
+        assert!((tri.area() - 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_voronoi_cells() {
+        let points = vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(2.0, 0.0),
+            Point2D::new(2.0, 2.0),
+            Point2D::new(0.0, 2.0),
+            Point2D::new(1.0, 1.0),
+        ];
+        let tri = Triangulation::delaunay(&points).unwrap();
+        let bounds = BoundingBox2::new(NPoint2::new(0.0, 0.0), NPoint2::new(2.0, 2.0));
+        let cells = tri.voronoi(&bounds);
+        assert_eq!(cells.len(), 5);
+
+        let centre = &cells[4];
+        assert_eq!(centre.site, 4);
+        assert!(centre.bounded);
+        assert!((Polygon2D::new(centre.vertices.clone()).signed_area() - 2.0).abs() < 1e-12);
+        assert!(cells[..4].iter().all(|cell| !cell.bounded));
+
+        // The cells tile the bounds
+        let total: f64 = cells.iter().map(|cell| Polygon2D::new(cell.vertices.clone()).signed_area()).sum();
+        assert!((total - 4.0).abs() < 1e-12);
+        for centre in tri.circumcenters() {
+            assert!(centre.distance_to(&Point2D::new(1.0, 1.0)) <= 1.0 + 1e-12);
+        }
+    }
+}
@@ -0,0 +1,642 @@
+//! Minimum enclosing shapes
+//!
+//! Tight bounds for nesting, stock-size estimation and fit checks:
+//! - [`min_area_rect`]: the smallest-area rectangle at any angle around 2D
+//!   points, by rotating calipers over their convex hull
+//! - [`min_volume_box`]: an oriented box around 3D points, trying each face
+//!   of their convex hull as the box's base
+//! - [`enclosing_circle`] and [`enclosing_sphere`]: the smallest circle or
+//!   sphere containing the points, by Welzl's algorithm
+//!
+//! The rectangle, circle and sphere are exact. The optimal 3D box need not
+//! sit flush on a hull face, so [`min_volume_box`] can be slightly larger
+//! than the true minimum for some shapes; it is exact for boxes, prisms and
+//! most machined parts, where one face of the part is the best base.
+
+use crate::core::precision::EPSILON;
+use crate::geometry::arc::Circle2D;
+use crate::geometry::hull::{convex_hull_2d, convex_hull_3d, ConvexHull3D};
+use crate::geometry::point::Point2D;
+use crate::geometry::solid::Sphere3D;
+use nalgebra::{Matrix3, Point3, Vector3};
+use serde::{Deserialize, Serialize};
+
+/// Rectangle at an angle, with `width >= height`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OrientedRect2D {
+    /// Center
+    pub center: Point2D,
+    /// Unit direction of the width, pointing into the right half-plane
+    pub axis: Point2D,
+    /// Length along `axis`
+    pub width: f64,
+    /// Length across `axis`
+    pub height: f64,
+}
+
+impl OrientedRect2D {
+    /// Angle of the width from the X axis, in (-π/2, π/2]
+    pub fn angle(&self) -> f64 {
+        self.axis.y.atan2(self.axis.x)
+    }
+
+    pub fn area(&self) -> f64 {
+        self.width * self.height
+    }
+
+    /// Corners, counter-clockwise
+    pub fn corners(&self) -> [Point2D; 4] {
+        let u = self.axis * (self.width / 2.0);
+        let v = Point2D::new(-self.axis.y, self.axis.x) * (self.height / 2.0);
+        [
+            self.center - u - v,
+            self.center + u - v,
+            self.center + u + v,
+            self.center - u + v,
+        ]
+    }
+
+    /// Whether a point is inside, or within `tolerance` of the outline
+    pub fn contains(&self, point: &Point2D, tolerance: f64) -> bool {
+        let d = *point - self.center;
+        let v = Point2D::new(-self.axis.y, self.axis.x);
+        d.dot(&self.axis).abs() <= self.width / 2.0 + tolerance
+            && d.dot(&v).abs() <= self.height / 2.0 + tolerance
+    }
+
+    /// Whether the rectangle fits on a sheet of the given size, turned
+    /// whichever way suits
+    pub fn fits_within(&self, width: f64, height: f64) -> bool {
+        let (long, short) = (width.max(height), width.min(height));
+        self.width <= long + EPSILON && self.height <= short + EPSILON
+    }
+}
+
+/// Box at an orientation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OrientedBox3D {
+    /// Center
+    pub center: Point3<f64>,
+    /// Unit edge directions, right-handed, longest extent first
+    pub axes: [Vector3<f64>; 3],
+    /// Edge lengths along each axis, descending
+    pub extents: [f64; 3],
+}
+
+impl OrientedBox3D {
+    pub fn volume(&self) -> f64 {
+        self.extents.iter().product()
+    }
+
+    /// Corners; bit 0, 1 and 2 of the index pick the positive side of
+    /// each axis
+    pub fn corners(&self) -> [Point3<f64>; 8] {
+        std::array::from_fn(|i| {
+            (0..3).fold(self.center, |p, k| {
+                let sign = if i & (1 << k) == 0 { -0.5 } else { 0.5 };
+                p + self.axes[k] * (self.extents[k] * sign)
+            })
+        })
+    }
+
+    /// Whether a point is inside, or within `tolerance` of a face
+    pub fn contains(&self, point: &Point3<f64>, tolerance: f64) -> bool {
+        let d = point - self.center;
+        (0..3).all(|k| d.dot(&self.axes[k]).abs() <= self.extents[k] / 2.0 + tolerance)
+    }
+
+    /// Whether the box fits inside stock of the given dimensions, in any
+    /// axis-aligned orientation
+    pub fn fits_within(&self, stock: [f64; 3]) -> bool {
+        let mut stock = stock;
+        stock.sort_by(|a, b| b.total_cmp(a));
+        self.extents
+            .iter()
+            .zip(stock)
+            .all(|(&e, s)| e <= s + EPSILON)
+    }
+}
+
+/// Smallest-area rectangle around `points`, at any angle
+///
+/// The best rectangle has a side flush with an edge of the convex hull, so
+/// each hull edge is tried in turn while three calipers track the extreme
+/// points ahead, beside and behind it. Collinear points give a rectangle of
+/// zero height and a single point one of zero size; no points give `None`.
+pub fn min_area_rect(points: &[Point2D]) -> Option<OrientedRect2D> {
+    let hull = convex_hull_2d(points);
+    let n = hull.len();
+    match n {
+        0 => return None,
+        1 => return Some(rect(hull[0], Point2D::new(1.0, 0.0), 0.0, 0.0)),
+        2 => {
+            let length = hull[0].distance_to(&hull[1]);
+            return Some(rect(
+                hull[0].midpoint(&hull[1]),
+                (hull[1] - hull[0]).normalize(),
+                length,
+                0.0,
+            ));
+        }
+        _ => {}
+    }
+
+    let along = |i: usize, u: Point2D, o: Point2D| (hull[i % n] - o).dot(&u);
+    let (mut ahead, mut beside, mut behind) = (0, 0, 0);
+    let mut best: Option<(f64, OrientedRect2D)> = None;
+    for i in 0..n {
+        let o = hull[i];
+        let u = (hull[(i + 1) % n] - o).normalize();
+        // Hull is counter-clockwise, so the inside is to the left
+        let v = Point2D::new(-u.y, u.x);
+        if i == 0 {
+            let extreme = |key: &dyn Fn(usize) -> f64| {
+                (0..n)
+                    .max_by(|&a, &b| key(a).total_cmp(&key(b)))
+                    .unwrap_or(0)
+            };
+            ahead = extreme(&|j| along(j, u, o));
+            beside = extreme(&|j| along(j, v, o));
+            behind = extreme(&|j| -along(j, u, o));
+        }
+        // Each caliper only ever moves forward around the hull
+        for _ in 0..n {
+            if along(ahead + 1, u, o) <= along(ahead, u, o) {
+                break;
+            }
+            ahead = (ahead + 1) % n;
+        }
+        for _ in 0..n {
+            if along(beside + 1, v, o) <= along(beside, v, o) {
+                break;
+            }
+            beside = (beside + 1) % n;
+        }
+        for _ in 0..n {
+            if along(behind + 1, u, o) >= along(behind, u, o) {
+                break;
+            }
+            behind = (behind + 1) % n;
+        }
+
+        let (min_u, max_u, max_v) = (along(behind, u, o), along(ahead, u, o), along(beside, v, o));
+        let area = (max_u - min_u) * max_v;
+        if best.as_ref().is_none_or(|(a, _)| area < *a) {
+            let center = o + u * ((min_u + max_u) / 2.0) + v * (max_v / 2.0);
+            best = Some((area, rect(center, u, max_u - min_u, max_v)));
+        }
+    }
+    best.map(|(_, rect)| rect)
+}
+
+/// Rectangle with the longer side along the axis, pointing right
+fn rect(center: Point2D, axis: Point2D, width: f64, height: f64) -> OrientedRect2D {
+    let (mut axis, width, height) = if height > width {
+        (Point2D::new(-axis.y, axis.x), height, width)
+    } else {
+        (axis, width, height)
+    };
+    if axis.x < 0.0 || (axis.x == 0.0 && axis.y < 0.0) {
+        axis = -axis;
+    }
+    OrientedRect2D {
+        center,
+        axis,
+        width,
+        height,
+    }
+}
+
+/// Oriented box around `points`
+///
+/// Each distinct face plane of the convex hull is tried as the base: the
+/// points are projected onto it, [`min_area_rect`] fits the footprint and
+/// the depth is the spread along the face normal. Coplanar points give a
+/// flat box, collinear points a box with one non-zero extent.
+pub fn min_volume_box(points: &[Point3<f64>]) -> Option<OrientedBox3D> {
+    let along_x = Vector3::x();
+    match convex_hull_3d(points) {
+        ConvexHull3D::Empty => None,
+        ConvexHull3D::Point(p) => Some(oriented_box(
+            p,
+            [Vector3::x(), Vector3::y(), Vector3::z()],
+            [0.0; 3],
+        )),
+        ConvexHull3D::Segment(a, b) => {
+            let u = (b - a).normalize();
+            let (v, w) = plane_basis(&u);
+            Some(oriented_box(
+                nalgebra::center(&a, &b),
+                [u, v, w],
+                [(b - a).norm(), 0.0, 0.0],
+            ))
+        }
+        ConvexHull3D::Polygon { vertices, normal } => {
+            let (rect, axes, center) = footprint(&vertices, &normal);
+            Some(oriented_box(center, axes, [rect.width, rect.height, 0.0]))
+        }
+        ConvexHull3D::Polyhedron { vertices, faces } => {
+            let mut normals: Vec<Vector3<f64>> = Vec::new();
+            for &[a, b, c] in &faces {
+                let Some(n) = (vertices[b] - vertices[a])
+                    .cross(&(vertices[c] - vertices[a]))
+                    .try_normalize(0.0)
+                else {
+                    continue;
+                };
+                if !normals.iter().any(|m| m.dot(&n) > 1.0 - 1e-9) {
+                    normals.push(n);
+                }
+            }
+            if normals.is_empty() {
+                normals.push(along_x);
+            }
+
+            let mut best: Option<OrientedBox3D> = None;
+            for n in &normals {
+                let (rect, axes, center) = footprint(&vertices, n);
+                let depths = vertices.iter().map(|p| p.coords.dot(n));
+                let (low, high) = depths.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), d| {
+                    (lo.min(d), hi.max(d))
+                });
+                let center = center + n * ((low + high) / 2.0 - center.coords.dot(n));
+                let candidate = oriented_box(center, axes, [rect.width, rect.height, high - low]);
+                if best
+                    .as_ref()
+                    .is_none_or(|b| candidate.volume() < b.volume())
+                {
+                    best = Some(candidate);
+                }
+            }
+            best
+        }
+    }
+}
+
+/// Two unit vectors completing a right-handed frame with `n`
+fn plane_basis(n: &Vector3<f64>) -> (Vector3<f64>, Vector3<f64>) {
+    let helper = if n.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    let e1 = n.cross(&helper).normalize();
+    let e2 = n.cross(&e1);
+    (e1, e2)
+}
+
+/// Minimum rectangle of the points projected along `n`, with its axes in 3D
+/// and its center on the plane through the origin
+fn footprint(
+    points: &[Point3<f64>],
+    n: &Vector3<f64>,
+) -> (OrientedRect2D, [Vector3<f64>; 3], Point3<f64>) {
+    let (e1, e2) = plane_basis(n);
+    let projected: Vec<Point2D> = points
+        .iter()
+        .map(|p| Point2D::new(p.coords.dot(&e1), p.coords.dot(&e2)))
+        .collect();
+    let rect = min_area_rect(&projected).unwrap_or(rect(
+        Point2D::origin(),
+        Point2D::new(1.0, 0.0),
+        0.0,
+        0.0,
+    ));
+    let u = e1 * rect.axis.x + e2 * rect.axis.y;
+    let v = n.cross(&u);
+    let center = Point3::from(e1 * rect.center.x + e2 * rect.center.y);
+    (rect, [u, v, *n], center)
+}
+
+/// Box with its axes sorted by extent and kept right-handed
+fn oriented_box(center: Point3<f64>, axes: [Vector3<f64>; 3], extents: [f64; 3]) -> OrientedBox3D {
+    let mut order = [0, 1, 2];
+    order.sort_by(|&a, &b| extents[b].total_cmp(&extents[a]));
+    let mut axes = order.map(|k| axes[k]);
+    if axes[0].cross(&axes[1]).dot(&axes[2]) < 0.0 {
+        axes[2] = -axes[2];
+    }
+    OrientedBox3D {
+        center,
+        axes,
+        extents: order.map(|k| extents[k]),
+    }
+}
+
+/// Points in a fixed pseudo-random order, for Welzl's expected linear time
+/// without depending on the input order
+fn shuffled<T: Copy>(points: &[T]) -> Vec<T> {
+    let mut points = points.to_vec();
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    for i in (1..points.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        points.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+    points
+}
+
+/// Smallest circle containing `points`; `None` for no points
+pub fn enclosing_circle(points: &[Point2D]) -> Option<Circle2D> {
+    let points = shuffled(points);
+    let first = *points.first()?;
+    let scale = points
+        .iter()
+        .map(|p| p.x.abs().max(p.y.abs()))
+        .fold(1.0, f64::max);
+    let tolerance = EPSILON * scale;
+    let outside = |c: &Circle2D, p: &Point2D| c.center.distance_to(p) > c.radius + tolerance;
+
+    let mut circle = Circle2D::new(first, 0.0);
+    for i in 0..points.len() {
+        if !outside(&circle, &points[i]) {
+            continue;
+        }
+        circle = Circle2D::new(points[i], 0.0);
+        for j in 0..i {
+            if !outside(&circle, &points[j]) {
+                continue;
+            }
+            circle = circle_through_two(points[i], points[j]);
+            for k in 0..j {
+                if outside(&circle, &points[k]) {
+                    circle = circle_through_three(points[i], points[j], points[k]);
+                }
+            }
+        }
+    }
+    Some(circle)
+}
+
+fn circle_through_two(a: Point2D, b: Point2D) -> Circle2D {
+    Circle2D::new(a.midpoint(&b), a.distance_to(&b) / 2.0)
+}
+
+/// Circumcircle, or the circle on the farthest pair when the points are
+/// collinear
+fn circle_through_three(a: Point2D, b: Point2D, c: Point2D) -> Circle2D {
+    let (ab, ac) = (b - a, c - a);
+    let d = 2.0 * ab.cross(&ac);
+    let scale = ab.dot(&ab).max(ac.dot(&ac));
+    if d.abs() <= EPSILON * scale {
+        return [
+            circle_through_two(a, b),
+            circle_through_two(a, c),
+            circle_through_two(b, c),
+        ]
+        .into_iter()
+        .max_by(|x, y| x.radius.total_cmp(&y.radius))
+        .unwrap_or(Circle2D::new(a, 0.0));
+    }
+    let (ab2, ac2) = (ab.dot(&ab), ac.dot(&ac));
+    let offset = Point2D::new(ac.y * ab2 - ab.y * ac2, ab.x * ac2 - ac.x * ab2) / d;
+    Circle2D::new(a + offset, offset.distance_to_origin())
+}
+
+/// Smallest sphere containing `points`; `None` for no points
+pub fn enclosing_sphere(points: &[Point3<f64>]) -> Option<Sphere3D> {
+    let points = shuffled(points);
+    let first = *points.first()?;
+    let scale = points.iter().map(|p| p.coords.amax()).fold(1.0, f64::max);
+    let tolerance = EPSILON * scale;
+    let outside =
+        |s: &Sphere3D, p: &Point3<f64>| nalgebra::distance(&s.center, p) > s.radius + tolerance;
+    let sphere = |center: Point3<f64>, radius: f64| Sphere3D { center, radius };
+
+    let mut ball = sphere(first, 0.0);
+    for i in 0..points.len() {
+        if !outside(&ball, &points[i]) {
+            continue;
+        }
+        ball = sphere(points[i], 0.0);
+        for j in 0..i {
+            if !outside(&ball, &points[j]) {
+                continue;
+            }
+            ball = sphere_through(&[points[i], points[j]]);
+            for k in 0..j {
+                if !outside(&ball, &points[k]) {
+                    continue;
+                }
+                ball = sphere_through(&[points[i], points[j], points[k]]);
+                for l in 0..k {
+                    if outside(&ball, &points[l]) {
+                        ball = sphere_through(&[points[i], points[j], points[k], points[l]]);
+                    }
+                }
+            }
+        }
+    }
+    Some(ball)
+}
+
+/// Smallest sphere with two to four points on its surface
+///
+/// Degenerate sets (collinear triples, coplanar quadruples) fall back to
+/// the smallest sphere through a subset that still contains them all.
+fn sphere_through(points: &[Point3<f64>]) -> Sphere3D {
+    let contains_all = |s: &Sphere3D| {
+        points
+            .iter()
+            .all(|p| nalgebra::distance(&s.center, p) <= s.radius * (1.0 + 1e-9) + EPSILON)
+    };
+    let smallest_subset = |size: usize| -> Sphere3D {
+        let n = points.len();
+        let subsets: Vec<Vec<Point3<f64>>> = match size {
+            2 => (0..n)
+                .flat_map(|i| (i + 1..n).map(move |j| vec![points[i], points[j]]))
+                .collect(),
+            _ => (0..n)
+                .map(|skip| {
+                    points
+                        .iter()
+                        .enumerate()
+                        .filter(|&(k, _)| k != skip)
+                        .map(|(_, &p)| p)
+                        .collect()
+                })
+                .collect(),
+        };
+        subsets
+            .iter()
+            .map(|subset| sphere_through(subset))
+            .filter(|s| contains_all(s))
+            .min_by(|a, b| a.radius.total_cmp(&b.radius))
+            .unwrap_or(Sphere3D {
+                center: points[0],
+                radius: 0.0,
+            })
+    };
+
+    let a = points[0];
+    match points.len() {
+        1 => Sphere3D {
+            center: a,
+            radius: 0.0,
+        },
+        2 => Sphere3D {
+            center: nalgebra::center(&a, &points[1]),
+            radius: nalgebra::distance(&a, &points[1]) / 2.0,
+        },
+        3 => {
+            let (ab, ac) = (points[1] - a, points[2] - a);
+            let n = ab.cross(&ac);
+            if n.norm_squared() <= EPSILON * ab.norm_squared().max(ac.norm_squared()).powi(2) {
+                return smallest_subset(2);
+            }
+            let offset = (n.cross(&ab) * ac.norm_squared() + ac.cross(&n) * ab.norm_squared())
+                / (2.0 * n.norm_squared());
+            Sphere3D {
+                center: a + offset,
+                radius: offset.norm(),
+            }
+        }
+        _ => {
+            let (ab, ac, ad) = (points[1] - a, points[2] - a, points[3] - a);
+            let m = Matrix3::from_rows(&[ab.transpose(), ac.transpose(), ad.transpose()]);
+            let rhs = Vector3::new(ab.norm_squared(), ac.norm_squared(), ad.norm_squared()) / 2.0;
+            let flat = m.determinant().abs() <= EPSILON * ab.norm() * ac.norm() * ad.norm();
+            match m.try_inverse() {
+                Some(inverse) if !flat => {
+                    let offset = inverse * rhs;
+                    Sphere3D {
+                        center: a + offset,
+                        radius: offset.norm(),
+                    }
+                }
+                _ => smallest_subset(3),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_4, FRAC_PI_6};
+
+    const TOL: f64 = 1e-9;
+
+    #[test]
+    fn test_min_area_rect_rotated() {
+        // A 4 x 2 rectangle turned 30°, with interior points
+        let (c, s) = (FRAC_PI_6.cos(), FRAC_PI_6.sin());
+        let turn = |x: f64, y: f64| Point2D::new(10.0 + x * c - y * s, 5.0 + x * s + y * c);
+        let mut points = vec![
+            turn(-2.0, -1.0),
+            turn(2.0, -1.0),
+            turn(2.0, 1.0),
+            turn(-2.0, 1.0),
+        ];
+        points.extend((0..20).map(|i| turn(-1.5 + 0.15 * i as f64, 0.5 * ((i % 3) as f64 - 1.0))));
+
+        let rect = min_area_rect(&points).unwrap();
+        assert!(
+            (rect.width - 4.0).abs() < TOL && (rect.height - 2.0).abs() < TOL,
+            "{:?}",
+            rect
+        );
+        assert!((rect.angle() - FRAC_PI_6).abs() < TOL);
+        assert!(rect.center.distance_to(&Point2D::new(10.0, 5.0)) < TOL);
+        assert!(points.iter().all(|p| rect.contains(p, TOL)));
+        assert!(rect.fits_within(2.1, 4.1));
+        assert!(!rect.fits_within(3.9, 3.9));
+
+        // A diamond's best rectangle is the square it is
+        let diamond = [
+            Point2D::new(1.0, 0.0),
+            Point2D::new(0.0, 1.0),
+            Point2D::new(-1.0, 0.0),
+            Point2D::new(0.0, -1.0),
+        ];
+        let rect = min_area_rect(&diamond).unwrap();
+        assert!((rect.area() - 2.0).abs() < TOL);
+        assert!((rect.angle().abs() - FRAC_PI_4).abs() < TOL);
+
+        assert!(min_area_rect(&[]).is_none());
+        let line = min_area_rect(&[Point2D::new(0.0, 0.0), Point2D::new(0.0, 3.0)]).unwrap();
+        assert_eq!((line.width, line.height), (3.0, 0.0));
+    }
+
+    #[test]
+    fn test_min_volume_box() {
+        // A 6 x 3 x 1 block turned about Z and tipped about X
+        let rotation = nalgebra::Rotation3::from_euler_angles(0.3, 0.0, 0.7);
+        let offset = Vector3::new(5.0, -2.0, 1.0);
+        let corners: Vec<Point3<f64>> = (0..8)
+            .map(|i| {
+                let local = Vector3::new(
+                    if i & 1 == 0 { -3.0 } else { 3.0 },
+                    if i & 2 == 0 { -1.5 } else { 1.5 },
+                    if i & 4 == 0 { -0.5 } else { 0.5 },
+                );
+                Point3::from(rotation * local + offset)
+            })
+            .collect();
+
+        let obb = min_volume_box(&corners).unwrap();
+        for (e, expected) in obb.extents.iter().zip([6.0, 3.0, 1.0]) {
+            assert!((e - expected).abs() < 1e-9, "{:?}", obb.extents);
+        }
+        assert!((obb.volume() - 18.0).abs() < 1e-8);
+        assert!(nalgebra::distance(&obb.center, &Point3::from(offset)) < 1e-9);
+        assert!(corners.iter().all(|p| obb.contains(p, 1e-9)));
+        assert!(obb.fits_within([1.0, 6.0, 3.0]));
+        assert!(!obb.fits_within([5.9, 3.0, 1.0]));
+        assert!(obb.axes[0].cross(&obb.axes[1]).dot(&obb.axes[2]) > 0.0);
+    }
+
+    #[test]
+    fn test_enclosing_circle_and_sphere() {
+        // Triangle with an obtuse angle: the circle is on its longest side
+        let points = [
+            Point2D::new(0.0, 0.0),
+            Point2D::new(10.0, 0.0),
+            Point2D::new(5.0, 1.0),
+        ];
+        let circle = enclosing_circle(&points).unwrap();
+        assert!(circle.center.distance_to(&Point2D::new(5.0, 0.0)) < TOL);
+        assert!((circle.radius - 5.0).abs() < TOL);
+
+        // Points on a circle plus interior noise
+        let ring: Vec<Point2D> = (0..50)
+            .map(|i| {
+                let a = i as f64 * 0.37;
+                let r = if i % 5 == 0 {
+                    3.0
+                } else {
+                    1.0 + (i % 4) as f64 * 0.4
+                };
+                Point2D::new(2.0 + r * a.cos(), -1.0 + r * a.sin())
+            })
+            .collect();
+        let circle = enclosing_circle(&ring).unwrap();
+        assert!(
+            circle.center.distance_to(&Point2D::new(2.0, -1.0)) < 1e-6,
+            "{:?}",
+            circle
+        );
+        assert!((circle.radius - 3.0).abs() < 1e-9);
+        assert!(enclosing_circle(&[]).is_none());
+
+        // Cube corners sit on the sphere through its diagonal
+        let cube: Vec<Point3<f64>> = (0..8)
+            .map(|i| Point3::new((i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64))
+            .chain([Point3::new(0.5, 0.5, 0.5), Point3::new(0.2, 0.9, 0.4)])
+            .collect();
+        let sphere = enclosing_sphere(&cube).unwrap();
+        assert!(nalgebra::distance(&sphere.center, &Point3::new(0.5, 0.5, 0.5)) < TOL);
+        assert!((sphere.radius - 3f64.sqrt() / 2.0).abs() < TOL);
+
+        // Coplanar square: the sphere is its circumcircle
+        let square = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 2.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+        ];
+        let sphere = enclosing_sphere(&square).unwrap();
+        assert!((sphere.radius - 2f64.sqrt()).abs() < TOL);
+    }
+}
@@ -0,0 +1,417 @@
+//! Fillets and chamfers
+//!
+//! [`fillet`] rounds the corner between two lines or arcs with an arc of a
+//! given radius tangent to both; [`chamfer`] cuts the corner off with a
+//! straight bevel set back given distances from it. Either way the two
+//! originals are trimmed, or extended if they stop short, so that they end
+//! exactly where the new piece starts.
+//!
+//! Two entities usually form more than one corner, so each is given a pick
+//! point, as if the user had clicked it: the part of the entity on the
+//! pick's side of the corner is the part that is kept. [`fillet`] and
+//! [`chamfer`] pick the end of each entity farther from the other one,
+//! which is what is wanted when the two meet, cross or nearly meet at
+//! their ends; [`fillet_picked`] and [`chamfer_picked`] take explicit picks.
+//!
+//! A zero radius or zero chamfer distances just trims or extends both
+//! entities to their intersection.
+
+use crate::core::precision::EPSILON;
+use crate::geometry::arc::Arc2D;
+use crate::geometry::fitting::FitSegment;
+use crate::geometry::line::LineSegment2D;
+use crate::geometry::point::Point2D;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::{PI, TAU};
+
+/// Outcome of a fillet or chamfer
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Corner {
+    /// First entity, trimmed or extended
+    pub first: FitSegment,
+    /// Second entity, trimmed or extended
+    pub second: FitSegment,
+    /// Fillet arc or chamfer line between them; `None` when the radius or
+    /// distances are zero and the two simply meet
+    pub joint: Option<FitSegment>,
+}
+
+/// Round the corner between two lines or arcs
+///
+/// Returns `None` if no arc of this radius touches both, or if the fillet
+/// would use up one of the entities entirely.
+pub fn fillet(a: &FitSegment, b: &FitSegment, radius: f64) -> Option<Corner> {
+    let (pick_a, pick_b) = far_ends(a, b);
+    fillet_picked(a, pick_a, b, pick_b, radius)
+}
+
+/// Round the corner between two lines or arcs on the side of the picks
+pub fn fillet_picked(
+    a: &FitSegment,
+    pick_a: Point2D,
+    b: &FitSegment,
+    pick_b: Point2D,
+    radius: f64,
+) -> Option<Corner> {
+    if radius < 0.0 || !radius.is_finite() {
+        return None;
+    }
+    let (ca, cb) = (Carrier::of(a), Carrier::of(b));
+    if radius < EPSILON {
+        return meet(a, pick_a, b, pick_b);
+    }
+
+    // The center is at the radius from both entities, i.e. where their
+    // offsets to either side cross
+    let mut candidates = Vec::new();
+    for side_a in [1.0, -1.0] {
+        for side_b in [1.0, -1.0] {
+            if let (Some(oa), Some(ob)) = (ca.offset(side_a * radius), cb.offset(side_b * radius)) {
+                candidates.extend(intersect(&oa, &ob));
+            }
+        }
+    }
+
+    // Prefer a center inside the corner the picks span: on the same side
+    // of each entity as the other entity's pick
+    let facing = |center: &Point2D| {
+        agrees(ca.side(*center), ca.side(pick_b)) && agrees(cb.side(*center), cb.side(pick_a))
+    };
+    let cost = |center: &Point2D| {
+        ca.foot(*center).distance_to(&pick_a) + cb.foot(*center).distance_to(&pick_b)
+    };
+    let center = nearest(candidates.iter().filter(|c| facing(c)).copied(), cost)
+        .or_else(|| nearest(candidates.iter().copied(), cost))?;
+
+    let (ta, tb) = (ca.foot(center), cb.foot(center));
+    let ccw = (ta - center).cross(&(tb - center)) > 0.0;
+    let arc = Arc2D::new(center, radius, center.angle_to(&ta), center.angle_to(&tb), ccw);
+    // The path runs along the first entity into the arc and out along the
+    // second, so each keeps the side its end of the arc points away from
+    let tangent = |p: Point2D| {
+        let t = Point2D::new(center.y - p.y, p.x - center.x);
+        if ccw { t } else { -t }
+    };
+    Some(Corner {
+        first: trim(a, ta, -tangent(ta))?,
+        second: trim(b, tb, tangent(tb))?,
+        joint: Some(FitSegment::Arc(arc)),
+    })
+}
+
+/// Bevel the corner between two lines or arcs, `distance_a` back along the
+/// first and `distance_b` back along the second
+///
+/// Distances along arcs are arc lengths. Returns `None` if the two do not
+/// intersect, even when extended.
+pub fn chamfer(a: &FitSegment, b: &FitSegment, distance_a: f64, distance_b: f64) -> Option<Corner> {
+    let (pick_a, pick_b) = far_ends(a, b);
+    chamfer_picked(a, pick_a, b, pick_b, distance_a, distance_b)
+}
+
+/// Bevel the corner between two lines or arcs on the side of the picks
+pub fn chamfer_picked(
+    a: &FitSegment,
+    pick_a: Point2D,
+    b: &FitSegment,
+    pick_b: Point2D,
+    distance_a: f64,
+    distance_b: f64,
+) -> Option<Corner> {
+    if distance_a < 0.0 || distance_b < 0.0 || !(distance_a + distance_b).is_finite() {
+        return None;
+    }
+    if distance_a < EPSILON && distance_b < EPSILON {
+        return meet(a, pick_a, b, pick_b);
+    }
+    let (ca, cb) = (Carrier::of(a), Carrier::of(b));
+    let corner = corner_point(&ca, pick_a, &cb, pick_b)?;
+
+    let (pa, heading_a) = ca.walk(corner, pick_a, distance_a);
+    let (pb, heading_b) = cb.walk(corner, pick_b, distance_b);
+    Some(Corner {
+        first: trim(a, pa, heading_a)?,
+        second: trim(b, pb, heading_b)?,
+        joint: Some(FitSegment::Line(LineSegment2D::new(pa, pb))),
+    })
+}
+
+/// Trim or extend both entities to the intersection nearest the picks
+fn meet(a: &FitSegment, pick_a: Point2D, b: &FitSegment, pick_b: Point2D) -> Option<Corner> {
+    let (ca, cb) = (Carrier::of(a), Carrier::of(b));
+    let corner = corner_point(&ca, pick_a, &cb, pick_b)?;
+    Some(Corner {
+        first: trim(a, corner, ca.walk(corner, pick_a, 0.0).1)?,
+        second: trim(b, corner, cb.walk(corner, pick_b, 0.0).1)?,
+        joint: None,
+    })
+}
+
+fn corner_point(ca: &Carrier, pick_a: Point2D, cb: &Carrier, pick_b: Point2D) -> Option<Point2D> {
+    nearest(intersect(ca, cb).into_iter(), |p| p.distance_to(&pick_a) + p.distance_to(&pick_b))
+}
+
+/// Default picks: the end of each entity farther from the other's ends
+fn far_ends(a: &FitSegment, b: &FitSegment) -> (Point2D, Point2D) {
+    let far = |s: &FitSegment, other: &FitSegment| {
+        let gap = |p: Point2D| p.distance_to(&other.start()).min(p.distance_to(&other.end()));
+        if gap(s.start()) > gap(s.end()) {
+            s.start()
+        } else {
+            s.end()
+        }
+    };
+    (far(a, b), far(b, a))
+}
+
+/// The part of `segment` that leaves `at` in the direction of `heading`,
+/// extended back to `at` if it starts beyond it; `None` if nothing of the
+/// segment lies that way
+fn trim(segment: &FitSegment, at: Point2D, heading: Point2D) -> Option<FitSegment> {
+    match segment {
+        FitSegment::Line(line) => {
+            let ahead = |p: Point2D| (p - at).dot(&heading);
+            let trimmed = if ahead(line.end) >= ahead(line.start) {
+                LineSegment2D::new(at, line.end)
+            } else {
+                LineSegment2D::new(line.start, at)
+            };
+            let far = ahead(line.end).max(ahead(line.start));
+            (far > EPSILON && trimmed.length() > EPSILON).then_some(FitSegment::Line(trimmed))
+        }
+        FitSegment::Arc(arc) => {
+            let angle = arc.center.angle_to(&at);
+            let radial = at - arc.center;
+            let forward = (Point2D::new(-radial.y, radial.x).dot(&heading) > 0.0) == arc.ccw;
+            let sweep = arc.sweep_angle();
+            let along = sweep_to(arc, angle);
+            // Off the arc, only the end next to `at` may be extended to it
+            let off = along > sweep + EPSILON;
+            let nearer_start = TAU - along < along - sweep;
+            let trimmed = if forward {
+                (!off || nearer_start).then(|| Arc2D::new(arc.center, arc.radius, angle, arc.end_angle, arc.ccw))
+            } else {
+                (!off || !nearer_start).then(|| Arc2D::new(arc.center, arc.radius, arc.start_angle, angle, arc.ccw))
+            }?;
+            (trimmed.length() > EPSILON).then_some(FitSegment::Arc(trimmed))
+        }
+    }
+}
+
+/// Angle swept from the start of `arc`, in its direction, to `angle`
+pub(crate) fn sweep_to(arc: &Arc2D, angle: f64) -> f64 {
+    let delta = if arc.ccw {
+        angle - arc.start_angle
+    } else {
+        arc.start_angle - angle
+    };
+    delta.rem_euclid(TAU)
+}
+
+fn nearest<I, F>(points: I, cost: F) -> Option<Point2D>
+where
+    I: Iterator<Item = Point2D>,
+    F: Fn(&Point2D) -> f64,
+{
+    points.min_by(|p, q| cost(p).total_cmp(&cost(q)))
+}
+
+/// Whether a point is on the expected side; a pick lying on the entity
+/// itself says nothing about the side
+fn agrees(side: f64, expected: f64) -> bool {
+    expected.abs() < EPSILON || side * expected > 0.0
+}
+
+/// The full line or circle a segment lies on
+pub(crate) enum Carrier {
+    Line { point: Point2D, dir: Point2D },
+    Circle { center: Point2D, radius: f64 },
+}
+
+impl Carrier {
+    pub(crate) fn of(segment: &FitSegment) -> Self {
+        match segment {
+            FitSegment::Line(line) => Carrier::Line {
+                point: line.start,
+                dir: (line.end - line.start).normalize(),
+            },
+            FitSegment::Arc(arc) => Carrier::Circle {
+                center: arc.center,
+                radius: arc.radius,
+            },
+        }
+    }
+
+    /// Parallel line or concentric circle; positive distances go left of a
+    /// line and outward from a circle
+    fn offset(&self, distance: f64) -> Option<Carrier> {
+        match *self {
+            Carrier::Line { point, dir } => Some(Carrier::Line {
+                point: point + Point2D::new(-dir.y, dir.x) * distance,
+                dir,
+            }),
+            Carrier::Circle { center, radius } => (radius + distance > EPSILON).then_some(Carrier::Circle {
+                center,
+                radius: radius + distance,
+            }),
+        }
+    }
+
+    /// Signed distance, with the sign convention of [`Self::offset`]
+    fn side(&self, p: Point2D) -> f64 {
+        match *self {
+            Carrier::Line { point, dir } => dir.cross(&(p - point)),
+            Carrier::Circle { center, radius } => p.distance_to(&center) - radius,
+        }
+    }
+
+    /// Closest point
+    fn foot(&self, p: Point2D) -> Point2D {
+        match *self {
+            Carrier::Line { point, dir } => point + dir * (p - point).dot(&dir),
+            Carrier::Circle { center, radius } => center + (p - center).normalize() * radius,
+        }
+    }
+
+    /// Point `distance` from `from` along the carrier, heading toward
+    /// `toward`, and the direction of travel there
+    fn walk(&self, from: Point2D, toward: Point2D, distance: f64) -> (Point2D, Point2D) {
+        match *self {
+            Carrier::Line { dir, .. } => {
+                let heading = if (toward - from).dot(&dir) < 0.0 { -dir } else { dir };
+                (from + heading * distance, heading)
+            }
+            Carrier::Circle { center, radius } => {
+                let start = center.angle_to(&from);
+                let turn = ((center.angle_to(&toward) - start + PI).rem_euclid(TAU) - PI).signum();
+                let angle = start + turn * distance / radius;
+                let heading = Point2D::new(-angle.sin(), angle.cos()) * turn;
+                (center + Point2D::from_polar(radius, angle), heading)
+            }
+        }
+    }
+}
+
+/// Points where two carriers meet
+pub(crate) fn intersect(a: &Carrier, b: &Carrier) -> Vec<Point2D> {
+    match (a, b) {
+        (&Carrier::Line { point: p, dir: d }, &Carrier::Line { point: q, dir: e }) => {
+            let denom = d.cross(&e);
+            if denom.abs() < EPSILON {
+                return Vec::new();
+            }
+            vec![p + d * ((q - p).cross(&e) / denom)]
+        }
+        (&Carrier::Line { point, dir }, &Carrier::Circle { center, radius })
+        | (&Carrier::Circle { center, radius }, &Carrier::Line { point, dir }) => {
+            let foot = point + dir * (center - point).dot(&dir);
+            let gap = foot.distance_to(&center);
+            if gap > radius + EPSILON {
+                return Vec::new();
+            }
+            let half = (radius * radius - gap * gap).max(0.0).sqrt();
+            if half < EPSILON {
+                vec![foot]
+            } else {
+                vec![foot + dir * half, foot - dir * half]
+            }
+        }
+        (&Carrier::Circle { center: c1, radius: r1 }, &Carrier::Circle { center: c2, radius: r2 }) => {
+            let d = c1.distance_to(&c2);
+            if d < EPSILON || d > r1 + r2 + EPSILON || d < (r1 - r2).abs() - EPSILON {
+                return Vec::new();
+            }
+            let along = (r1 * r1 - r2 * r2 + d * d) / (2.0 * d);
+            let half = (r1 * r1 - along * along).max(0.0).sqrt();
+            let u = (c2 - c1) / d;
+            let base = c1 + u * along;
+            let n = Point2D::new(-u.y, u.x);
+            if half < EPSILON {
+                vec![base]
+            } else {
+                vec![base + n * half, base - n * half]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOL: f64 = 1e-9;
+
+    fn line(x1: f64, y1: f64, x2: f64, y2: f64) -> FitSegment {
+        FitSegment::Line(LineSegment2D::new(Point2D::new(x1, y1), Point2D::new(x2, y2)))
+    }
+
+    fn touches(p: Point2D, segment: &FitSegment) -> bool {
+        p.approx_eq_eps(&segment.start(), 1e-6) || p.approx_eq_eps(&segment.end(), 1e-6)
+    }
+
+    /// Trimmed entities end where the joint starts and ends
+    fn assert_connected(corner: &Corner) {
+        let joint = corner.joint.expect("joint");
+        assert!(touches(joint.start(), &corner.first), "{:?}", corner);
+        assert!(touches(joint.end(), &corner.second), "{:?}", corner);
+    }
+
+    #[test]
+    fn test_line_line_fillet() {
+        let corner = fillet(&line(0.0, 0.0, 10.0, 0.0), &line(0.0, 0.0, 0.0, 10.0), 2.0).unwrap();
+        assert_connected(&corner);
+        assert_eq!(corner.first, line(2.0, 0.0, 10.0, 0.0));
+        assert_eq!(corner.second, line(0.0, 2.0, 0.0, 10.0));
+        let FitSegment::Arc(arc) = corner.joint.unwrap() else { panic!() };
+        assert!(arc.center.approx_eq_eps(&Point2D::new(2.0, 2.0), TOL));
+        assert!((arc.sweep_angle() - PI / 2.0).abs() < TOL);
+
+        // Lines that stop short are extended; zero radius just meets
+        let corner = fillet(&line(3.0, 0.0, 10.0, 0.0), &line(0.0, 4.0, 0.0, 10.0), 1.0).unwrap();
+        assert_eq!(corner.first, line(1.0, 0.0, 10.0, 0.0));
+        let corner = fillet(&line(3.0, 0.0, 10.0, 0.0), &line(0.0, 4.0, 0.0, 10.0), 0.0).unwrap();
+        assert!(corner.joint.is_none());
+        assert_eq!(corner.second, line(0.0, 0.0, 0.0, 10.0));
+
+        assert!(fillet(&line(0.0, 0.0, 10.0, 0.0), &line(0.0, 0.0, 0.0, 10.0), 20.0).is_none());
+    }
+
+    #[test]
+    fn test_line_arc_and_arc_arc_fillets() {
+        let base = line(-10.0, 0.0, 10.0, 0.0);
+        let dome = FitSegment::Arc(Arc2D::new(Point2D::new(0.0, 5.0), 3.0, -PI / 2.0, 0.0, true));
+        let corner = fillet_picked(&base, Point2D::new(10.0, 0.0), &dome, Point2D::new(3.0, 5.0), 2.0)
+            .unwrap();
+        assert_connected(&corner);
+        let FitSegment::Arc(arc) = corner.joint.unwrap() else { panic!() };
+        assert!(arc.center.approx_eq_eps(&Point2D::new(4.0, 2.0), TOL));
+        assert!(corner.first.start().approx_eq_eps(&Point2D::new(4.0, 0.0), TOL));
+        assert!(touches(Point2D::new(2.4, 3.2), &corner.second));
+
+        let left = FitSegment::Arc(Arc2D::new(Point2D::new(-4.0, 0.0), 3.0, 0.0, PI, true));
+        let right = FitSegment::Arc(Arc2D::new(Point2D::new(4.0, 0.0), 3.0, 0.0, PI, true));
+        let corner = fillet_picked(&left, Point2D::new(-4.0, 3.0), &right, Point2D::new(4.0, 3.0), 2.0)
+            .unwrap();
+        assert_connected(&corner);
+        let FitSegment::Arc(arc) = corner.joint.unwrap() else { panic!() };
+        assert!(arc.center.approx_eq_eps(&Point2D::new(0.0, 3.0), TOL));
+        assert!(corner.first.start().approx_eq_eps(&Point2D::new(-1.6, 1.8), TOL));
+    }
+
+    #[test]
+    fn test_chamfer() {
+        let corner = chamfer(&line(-1.0, 0.0, 10.0, 0.0), &line(0.0, -1.0, 0.0, 10.0), 2.0, 3.0).unwrap();
+        assert_connected(&corner);
+        assert_eq!(corner.first, line(2.0, 0.0, 10.0, 0.0));
+        assert_eq!(corner.second, line(0.0, 3.0, 0.0, 10.0));
+        assert_eq!(corner.joint, Some(line(2.0, 0.0, 0.0, 3.0)));
+
+        let arc = FitSegment::Arc(Arc2D::new(Point2D::origin(), 5.0, 0.0, PI / 2.0, true));
+        let corner = chamfer(&line(0.0, 0.0, 10.0, 0.0), &arc, 1.0, PI).unwrap();
+        assert_connected(&corner);
+        let FitSegment::Arc(trimmed) = corner.second else { panic!() };
+        assert!((trimmed.sweep_angle() - (PI / 2.0 - PI / 5.0)).abs() < TOL);
+
+        assert!(chamfer(&line(0.0, 0.0, 10.0, 0.0), &line(0.0, 1.0, 10.0, 1.0), 1.0, 1.0).is_none());
+    }
+}
@@ -0,0 +1,578 @@
+//! Curve fitting and simplification
+//!
+//! Reduces dense geometry to fewer, better-behaved primitives:
+//! - [`fit_arcs`] replaces runs of polyline vertices with lines and arcs
+//!   that stay within a tolerance of the original
+//! - [`fit_bspline`] and [`fit_bspline_within`] least-squares fit a clamped
+//!   B-spline to sampled points, and [`reduce_control_points`] uses them to
+//!   refit an existing spline with fewer control points
+//! - [`spline_to_arcs`] converts a spline to a line/arc polyline, the form
+//!   CNC, laser and plasma controllers accept (G1/G2/G3 moves)
+
+use crate::core::precision::EPSILON;
+use crate::geometry::arc::Arc2D;
+use crate::geometry::curve::{BSpline, NurbsCurve};
+use crate::geometry::line::LineSegment2D;
+use crate::geometry::point::Point2D;
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Points sampled per knot span when a spline is approximated by a polyline
+pub const SAMPLES_PER_SPAN: usize = 32;
+
+/// Longest arc [`fit_arcs`] will produce; near-full turns through three
+/// sample points are numerically ambiguous
+const MAX_ARC_SWEEP: f64 = 1.5 * PI;
+
+/// A line or arc produced by fitting
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FitSegment {
+    /// Straight segment
+    Line(LineSegment2D),
+    /// Circular arc
+    Arc(Arc2D),
+}
+
+impl FitSegment {
+    /// Start point
+    pub fn start(&self) -> Point2D {
+        match self {
+            FitSegment::Line(line) => line.start,
+            FitSegment::Arc(arc) => arc.start_point(),
+        }
+    }
+
+    /// End point
+    pub fn end(&self) -> Point2D {
+        match self {
+            FitSegment::Line(line) => line.end,
+            FitSegment::Arc(arc) => arc.end_point(),
+        }
+    }
+
+    /// Polyline bulge (tangent of a quarter of the sweep, negative when
+    /// clockwise); zero for lines
+    pub fn bulge(&self) -> f64 {
+        match self {
+            FitSegment::Line(_) => 0.0,
+            FitSegment::Arc(arc) => {
+                let bulge = (arc.sweep_angle() / 4.0).tan();
+                if arc.ccw {
+                    bulge
+                } else {
+                    -bulge
+                }
+            }
+        }
+    }
+
+    /// Length along the segment
+    pub fn length(&self) -> f64 {
+        match self {
+            FitSegment::Line(line) => line.length(),
+            FitSegment::Arc(arc) => arc.length(),
+        }
+    }
+}
+
+/// Polyline vertex with the bulge of the segment that starts at it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ArcVertex {
+    /// Vertex position
+    pub point: Point2D,
+    /// Bulge of the outgoing segment (0 = straight)
+    pub bulge: f64,
+}
+
+/// Polyline made of lines and arcs, stored as bulged vertices
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ArcPolyline {
+    /// Vertices
+    pub vertices: Vec<ArcVertex>,
+    /// Whether the last vertex connects back to the first
+    pub closed: bool,
+}
+
+impl ArcPolyline {
+    /// Build from consecutive segments
+    pub fn from_segments(segments: &[FitSegment], closed: bool) -> Self {
+        let mut vertices: Vec<ArcVertex> = segments
+            .iter()
+            .map(|segment| ArcVertex {
+                point: segment.start(),
+                bulge: segment.bulge(),
+            })
+            .collect();
+        if !closed {
+            if let Some(last) = segments.last() {
+                vertices.push(ArcVertex {
+                    point: last.end(),
+                    bulge: 0.0,
+                });
+            }
+        }
+        Self { vertices, closed }
+    }
+
+    /// Expand the bulged vertices back into lines and arcs
+    pub fn segments(&self) -> Vec<FitSegment> {
+        let count = self.vertices.len();
+        if count < 2 {
+            return Vec::new();
+        }
+        let segment_count = if self.closed { count } else { count - 1 };
+        (0..segment_count)
+            .map(|i| {
+                let start = self.vertices[i];
+                let end = self.vertices[(i + 1) % count];
+                bulge_segment(start.point, end.point, start.bulge)
+            })
+            .collect()
+    }
+
+    /// Number of arc segments
+    pub fn arc_count(&self) -> usize {
+        let count = self.vertices.len();
+        let segment_count = if self.closed {
+            count
+        } else {
+            count.saturating_sub(1)
+        };
+        self.vertices[..segment_count]
+            .iter()
+            .filter(|vertex| vertex.bulge.abs() > EPSILON)
+            .count()
+    }
+
+    /// Total length
+    pub fn length(&self) -> f64 {
+        self.segments().iter().map(FitSegment::length).sum()
+    }
+}
+
+/// Greedily replace runs of `points` with the longest line or arc that keeps
+/// every point, and every chord between them, within `tolerance`
+///
+/// Lines are preferred over arcs when both fit.
+pub fn fit_arcs(points: &[Point2D], tolerance: f64) -> Vec<FitSegment> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+
+    while start + 1 < points.len() {
+        let mut end = start + 1;
+        let mut best = FitSegment::Line(LineSegment2D::new(points[start], points[end]));
+        for candidate in start + 2..points.len() {
+            match fit_run(&points[start..=candidate], tolerance) {
+                Some(segment) => {
+                    best = segment;
+                    end = candidate;
+                }
+                None => break,
+            }
+        }
+        segments.push(best);
+        start = end;
+    }
+
+    segments
+}
+
+/// Fit lines and arcs to `points` and return them as a bulged polyline
+pub fn fit_arc_polyline(points: &[Point2D], tolerance: f64, closed: bool) -> ArcPolyline {
+    let mut run = points.to_vec();
+    if closed && run.len() > 2 && !run[0].approx_eq(&run[run.len() - 1]) {
+        run.push(run[0]);
+    }
+    ArcPolyline::from_segments(&fit_arcs(&run, tolerance), closed)
+}
+
+fn fit_run(run: &[Point2D], tolerance: f64) -> Option<FitSegment> {
+    let first = run[0];
+    let last = run[run.len() - 1];
+
+    let chord = LineSegment2D::new(first, last);
+    if run.iter().all(|p| chord.distance_to_point(p) <= tolerance) {
+        return Some(FitSegment::Line(chord));
+    }
+
+    let arc = Arc2D::from_three_points(first, run[run.len() / 2], last)?;
+    let sweep = arc.sweep_angle();
+    if sweep > MAX_ARC_SWEEP {
+        return None;
+    }
+
+    // Interior points must sit on the circle and advance along the arc
+    let mut previous = 0.0;
+    for point in &run[1..run.len() - 1] {
+        if (arc.center.distance_to(point) - arc.radius).abs() > tolerance {
+            return None;
+        }
+        let angle = (point.y - arc.center.y).atan2(point.x - arc.center.x);
+        let offset = if arc.ccw {
+            angle - arc.start_angle
+        } else {
+            arc.start_angle - angle
+        }
+        .rem_euclid(2.0 * PI);
+        if offset < previous || offset > sweep {
+            return None;
+        }
+        previous = offset;
+    }
+
+    // ...and the arc must not bow away from the original chords
+    let fits = run.windows(2).all(|pair| {
+        let half = pair[0].distance_to(&pair[1]) / 2.0;
+        let sagitta = arc.radius - (arc.radius * arc.radius - half * half).max(0.0).sqrt();
+        sagitta <= tolerance
+    });
+    fits.then_some(FitSegment::Arc(arc))
+}
+
+fn bulge_segment(start: Point2D, end: Point2D, bulge: f64) -> FitSegment {
+    let chord = end - start;
+    let length = chord.x.hypot(chord.y);
+    if bulge.abs() < EPSILON || length < EPSILON {
+        return FitSegment::Line(LineSegment2D::new(start, end));
+    }
+
+    let sweep = 4.0 * bulge.abs().atan();
+    let radius = length / (2.0 * (sweep / 2.0).sin());
+    let offset = radius * (sweep / 2.0).cos() * bulge.signum();
+    let normal = Point2D::new(-chord.y / length, chord.x / length);
+    let center = start.midpoint(&end) + normal * offset;
+
+    FitSegment::Arc(Arc2D::new(
+        center,
+        radius,
+        (start.y - center.y).atan2(start.x - center.x),
+        (end.y - center.y).atan2(end.x - center.x),
+        bulge > 0.0,
+    ))
+}
+
+/// Least-squares fit a clamped B-spline with `control_count` control points
+/// to `points`
+///
+/// The curve interpolates the first and last point. Returns `None` when
+/// there are too few points for the requested count or the system is
+/// singular.
+pub fn fit_bspline(points: &[Point2D], degree: usize, control_count: usize) -> Option<BSpline> {
+    let params = chord_length_parameters(points)?;
+    fit_with_parameters(points, &params, degree, control_count)
+}
+
+/// Fit the B-spline with the fewest control points (at most
+/// `max_control_points`) whose deviation from `points` stays within
+/// `tolerance`
+pub fn fit_bspline_within(
+    points: &[Point2D],
+    degree: usize,
+    tolerance: f64,
+    max_control_points: usize,
+) -> Option<BSpline> {
+    let params = chord_length_parameters(points)?;
+    let mut low = degree + 1;
+    let mut high = max_control_points.min(points.len());
+    let mut best = None;
+
+    // Deviation falls (almost always) monotonically with the control point
+    // count, so bisect rather than trying every count
+    while low <= high {
+        let count = low + (high - low) / 2;
+        let fitted = fit_with_parameters(points, &params, degree, count)
+            .filter(|spline| max_deviation(spline, points, &params) <= tolerance);
+        match fitted {
+            Some(spline) => {
+                best = Some(spline);
+                if count == degree + 1 {
+                    break;
+                }
+                high = count - 1;
+            }
+            None => low = count + 1,
+        }
+    }
+
+    best
+}
+
+/// Refit `spline` with fewer control points, keeping it within `tolerance`
+/// of the original; `None` when no reduction is possible
+pub fn reduce_control_points(spline: &BSpline, tolerance: f64) -> Option<BSpline> {
+    let count = spline.control_points.len();
+    if count <= spline.degree + 1 {
+        return None;
+    }
+    let samples = spline.to_polyline((count - spline.degree) * SAMPLES_PER_SPAN);
+    fit_bspline_within(&samples, spline.degree, tolerance, count - 1)
+}
+
+/// Approximate a NURBS curve by a non-rational B-spline with fewer control
+/// points, keeping it within `tolerance`
+pub fn reduce_nurbs_control_points(curve: &NurbsCurve, tolerance: f64) -> Option<BSpline> {
+    let count = curve.control_points.len();
+    if count <= curve.degree + 1 {
+        return None;
+    }
+    let samples = curve.to_polyline((count - curve.degree) * SAMPLES_PER_SPAN);
+    fit_bspline_within(&samples, curve.degree, tolerance, count - 1)
+}
+
+/// Convert a spline to lines and arcs within `tolerance`
+pub fn spline_to_arcs(spline: &BSpline, tolerance: f64) -> ArcPolyline {
+    let spans = spline
+        .control_points
+        .len()
+        .saturating_sub(spline.degree)
+        .max(1);
+    samples_to_arcs(&spline.to_polyline(spans * SAMPLES_PER_SPAN), tolerance)
+}
+
+/// Convert a NURBS curve to lines and arcs within `tolerance`
+pub fn nurbs_to_arcs(curve: &NurbsCurve, tolerance: f64) -> ArcPolyline {
+    let spans = curve
+        .control_points
+        .len()
+        .saturating_sub(curve.degree)
+        .max(1);
+    samples_to_arcs(&curve.to_polyline(spans * SAMPLES_PER_SPAN), tolerance)
+}
+
+fn samples_to_arcs(samples: &[Point2D], tolerance: f64) -> ArcPolyline {
+    let closed = samples.len() > 2 && samples[0].approx_eq(&samples[samples.len() - 1]);
+    // Half the budget goes to the sampling itself
+    fit_arc_polyline(samples, tolerance / 2.0, closed)
+}
+
+fn chord_length_parameters(points: &[Point2D]) -> Option<Vec<f64>> {
+    if points.len() < 2 {
+        return None;
+    }
+    let mut params = Vec::with_capacity(points.len());
+    let mut total = 0.0;
+    params.push(0.0);
+    for pair in points.windows(2) {
+        total += pair[0].distance_to(&pair[1]);
+        params.push(total);
+    }
+    if total < EPSILON {
+        return None;
+    }
+    params.iter_mut().for_each(|t| *t /= total);
+    Some(params)
+}
+
+/// Knot vector averaged over the fitting parameters (Piegl & Tiller 9.69),
+/// which keeps every span populated with samples
+fn averaged_knots(params: &[f64], degree: usize, control_count: usize) -> Vec<f64> {
+    let interior = control_count - degree - 1;
+    let step = params.len() as f64 / (interior + 1) as f64;
+    let mut knots = vec![0.0; degree + 1];
+    for j in 1..=interior {
+        let position = j as f64 * step;
+        let i = position.floor() as usize;
+        let alpha = position - i as f64;
+        knots.push((1.0 - alpha) * params[i - 1] + alpha * params[i]);
+    }
+    knots.extend(std::iter::repeat_n(1.0, degree + 1));
+    knots
+}
+
+fn fit_with_parameters(
+    points: &[Point2D],
+    params: &[f64],
+    degree: usize,
+    control_count: usize,
+) -> Option<BSpline> {
+    if degree == 0 || control_count <= degree || control_count > points.len() {
+        return None;
+    }
+
+    let first = points[0];
+    let last = points[points.len() - 1];
+    let mut spline = BSpline {
+        control_points: vec![first; control_count],
+        knots: averaged_knots(params, degree, control_count),
+        degree,
+    };
+    spline.control_points[control_count - 1] = last;
+
+    let unknowns = control_count - 2;
+    if unknowns > 0 {
+        // Endpoints are fixed; solve the normal equations for the rest
+        let rows = points.len() - 2;
+        let mut basis_matrix = DMatrix::<f64>::zeros(rows, unknowns);
+        let mut rhs_x = DVector::<f64>::zeros(rows);
+        let mut rhs_y = DVector::<f64>::zeros(rows);
+
+        for row in 0..rows {
+            let t = params[row + 1];
+            let span = spline.find_knot_span(t);
+            let mut residual = points[row + 1];
+            for (offset, value) in spline.basis_functions(span, t).into_iter().enumerate() {
+                let index = span - degree + offset;
+                if index == 0 {
+                    residual = residual - first * value;
+                } else if index == control_count - 1 {
+                    residual = residual - last * value;
+                } else {
+                    basis_matrix[(row, index - 1)] = value;
+                }
+            }
+            rhs_x[row] = residual.x;
+            rhs_y[row] = residual.y;
+        }
+
+        let transpose = basis_matrix.transpose();
+        let normal = &transpose * &basis_matrix;
+        let solve = |rhs: DVector<f64>| {
+            let rhs = &transpose * rhs;
+            match normal.clone().cholesky() {
+                Some(cholesky) => Some(cholesky.solve(&rhs)),
+                None => normal.clone().lu().solve(&rhs),
+            }
+        };
+        let xs = solve(rhs_x)?;
+        let ys = solve(rhs_y)?;
+        for i in 0..unknowns {
+            spline.control_points[i + 1] = Point2D::new(xs[i], ys[i]);
+        }
+    }
+
+    Some(spline)
+}
+
+/// Largest distance between each point and the curve at its fitting
+/// parameter, an upper bound on the true deviation
+fn max_deviation(spline: &BSpline, points: &[Point2D], params: &[f64]) -> f64 {
+    points
+        .iter()
+        .zip(params)
+        .map(|(point, &t)| point.distance_to(&spline.evaluate(t)))
+        .fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::line::Polyline2D;
+
+    fn arc_points(center: Point2D, radius: f64, from: f64, to: f64, count: usize) -> Vec<Point2D> {
+        (0..=count)
+            .map(|i| {
+                let angle = from + (to - from) * i as f64 / count as f64;
+                Point2D::new(
+                    center.x + radius * angle.cos(),
+                    center.y + radius * angle.sin(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fit_arcs_line_and_arc() {
+        // Straight run into a quarter circle
+        let mut points: Vec<Point2D> = (0..=10)
+            .map(|i| Point2D::new(i as f64 - 10.0, -5.0))
+            .collect();
+        points.extend(
+            arc_points(Point2D::origin(), 5.0, -PI / 2.0, 0.0, 20)
+                .into_iter()
+                .skip(1),
+        );
+
+        let segments = fit_arcs(&points, 0.01);
+        assert_eq!(segments.len(), 2);
+        assert!(matches!(segments[0], FitSegment::Line(_)));
+        match segments[1] {
+            FitSegment::Arc(arc) => {
+                assert!((arc.radius - 5.0).abs() < 1e-6);
+                assert!(arc.ccw);
+            }
+            _ => panic!("expected arc"),
+        }
+    }
+
+    #[test]
+    fn test_arc_polyline_bulge_round_trip() {
+        let points = arc_points(Point2D::new(2.0, 3.0), 4.0, PI, PI / 4.0, 30);
+        let polyline = fit_arc_polyline(&points, 0.01, false);
+        assert_eq!(polyline.vertices.len(), 2);
+        assert_eq!(polyline.arc_count(), 1);
+        assert!(polyline.vertices[0].bulge < 0.0);
+
+        match polyline.segments()[0] {
+            FitSegment::Arc(arc) => {
+                assert!(arc.center.approx_eq_eps(&Point2D::new(2.0, 3.0), 1e-6));
+                assert!((arc.radius - 4.0).abs() < 1e-6);
+            }
+            _ => panic!("expected arc"),
+        }
+        assert!((polyline.length() - 4.0 * 0.75 * PI).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_reduce_control_points() {
+        let control_points: Vec<Point2D> = (0..40)
+            .map(|i| {
+                let x = i as f64 * 0.25;
+                Point2D::new(x, x.sin())
+            })
+            .collect();
+        let spline = BSpline::clamped(control_points, 3).unwrap();
+
+        let reduced = reduce_control_points(&spline, 0.01).unwrap();
+        assert!(reduced.control_points.len() < 20);
+
+        let (t_min, t_max) = spline.parameter_range();
+        let dense = Polyline2D::open(spline.to_polyline(2000));
+        for point in reduced.to_polyline(400) {
+            assert!(dense.distance_to_point(&point).unwrap() <= 0.01 + 1e-6);
+        }
+        assert!(spline.evaluate(t_min).approx_eq(&reduced.control_points[0]));
+        assert!(spline
+            .evaluate(t_max)
+            .approx_eq(reduced.control_points.last().unwrap()));
+    }
+
+    #[test]
+    fn test_spline_to_arcs() {
+        let spline = BSpline::clamped(
+            vec![
+                Point2D::new(0.0, 0.0),
+                Point2D::new(2.0, 4.0),
+                Point2D::new(6.0, -2.0),
+                Point2D::new(9.0, 3.0),
+                Point2D::new(12.0, 0.0),
+            ],
+            3,
+        )
+        .unwrap();
+
+        let tolerance = 0.01;
+        let arcs = spline_to_arcs(&spline, tolerance);
+        let dense = Polyline2D::open(spline.to_polyline(2000));
+        assert!(arcs.vertices.len() < 2 * SAMPLES_PER_SPAN);
+        assert!(arcs.arc_count() > 0);
+
+        for segment in arcs.segments() {
+            for i in 0..=8 {
+                let t = i as f64 / 8.0;
+                let point = match segment {
+                    FitSegment::Line(line) => line.point_at(t),
+                    FitSegment::Arc(arc) => {
+                        let sweep = if arc.ccw {
+                            arc.sweep_angle()
+                        } else {
+                            -arc.sweep_angle()
+                        };
+                        arc.point_at_angle(arc.start_angle + sweep * t)
+                    }
+                };
+                assert!(dense.distance_to_point(&point).unwrap() <= tolerance);
+            }
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Hatch patterns and hatch line generation
+//!
+//! A [`HatchPattern`] is a set of [`PatternLine`] families as defined in an
+//! AutoCAD `.pat` file: each family is an infinite set of parallel lines,
+//! optionally dashed. [`hatch_lines`] lays the families across a set of
+//! boundary loops and clips them to the hatched area.
+//!
+//! Loops nested inside other loops are islands. Which of them the hatch
+//! respects is chosen by [`HatchStyle`], the same three styles AutoCAD
+//! offers; within the loops kept, the area hatched alternates with nesting
+//! depth (even-odd).
+
+use crate::geometry::line::LineSegment2D;
+use crate::geometry::point::Point2D;
+use crate::geometry::polygon::Polygon2D;
+use serde::{Deserialize, Serialize};
+
+/// Spacing of the built-in patterns at scale 1, in drawing units
+pub const PATTERN_SPACING: f64 = 3.175;
+
+/// Most lines laid for one pattern family
+pub const MAX_ROWS_PER_FAMILY: usize = 100_000;
+
+/// One family of parallel lines in a hatch pattern, as in a `.pat` file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatternLine {
+    /// Line angle in radians
+    pub angle: f64,
+    /// A point one of the lines passes through
+    pub origin: [f64; 2],
+    /// Shift from one line to the next: along the line, then across it
+    pub offset: [f64; 2],
+    /// Dash pattern: positive dashes, negative gaps, zero dots; empty for
+    /// solid lines
+    pub dashes: Vec<f64>,
+}
+
+impl PatternLine {
+    fn solid(angle_degrees: f64) -> Self {
+        Self {
+            angle: angle_degrees.to_radians(),
+            origin: [0.0, 0.0],
+            offset: [0.0, PATTERN_SPACING],
+            dashes: Vec::new(),
+        }
+    }
+}
+
+/// Hatch pattern definition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HatchPattern {
+    /// Pattern name
+    pub name: String,
+    /// Description from the `.pat` header
+    #[serde(default)]
+    pub description: String,
+    /// Line families
+    pub lines: Vec<PatternLine>,
+}
+
+impl HatchPattern {
+    /// Built-in pattern by name (case-insensitive)
+    pub fn builtin(name: &str) -> Option<Self> {
+        let lines = match name.to_uppercase().as_str() {
+            "LINE" => vec![PatternLine::solid(0.0)],
+            "ANSI31" => vec![PatternLine::solid(45.0)],
+            "NET" => vec![PatternLine::solid(0.0), PatternLine::solid(90.0)],
+            "ANSI37" => vec![PatternLine::solid(45.0), PatternLine::solid(135.0)],
+            "DASH" => vec![PatternLine {
+                dashes: vec![PATTERN_SPACING, -PATTERN_SPACING],
+                offset: [PATTERN_SPACING, PATTERN_SPACING],
+                ..PatternLine::solid(0.0)
+            }],
+            "DOTS" => vec![PatternLine {
+                dashes: vec![0.0, -PATTERN_SPACING / 2.0],
+                offset: [PATTERN_SPACING / 4.0, PATTERN_SPACING / 2.0],
+                ..PatternLine::solid(0.0)
+            }],
+            _ => return None,
+        };
+        Some(Self {
+            name: name.to_uppercase(),
+            description: String::new(),
+            lines,
+        })
+    }
+
+    /// Built-in pattern by name; unknown names draw as ANSI31
+    pub fn builtin_or_default(name: &str) -> Self {
+        Self::builtin(name).unwrap_or_else(|| Self::builtin("ANSI31").unwrap())
+    }
+}
+
+/// Which nested boundary loops a hatch respects
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HatchStyle {
+    /// Every loop; hatching alternates from the outside in
+    #[default]
+    Normal,
+    /// The outer loops and the islands directly inside them
+    Outer,
+    /// Only the outer loops; islands are hatched over
+    Ignore,
+}
+
+/// Nesting depth of each loop: how many of the other loops contain it
+pub fn island_depths(loops: &[Vec<Point2D>]) -> Vec<usize> {
+    let polygons: Vec<Polygon2D> = loops.iter().map(|l| Polygon2D::new(l.clone())).collect();
+    loops
+        .iter()
+        .enumerate()
+        .map(|(i, l)| {
+            let Some(sample) = l.first() else {
+                return 0;
+            };
+            polygons
+                .iter()
+                .enumerate()
+                .filter(|&(j, p)| j != i && p.vertices.len() > 2 && p.contains_point(sample))
+                .count()
+        })
+        .collect()
+}
+
+/// Indices of the loops a hatch with `style` clips against
+pub fn active_loops(loops: &[Vec<Point2D>], style: HatchStyle) -> Vec<usize> {
+    let max_depth = match style {
+        HatchStyle::Normal => return (0..loops.len()).collect(),
+        HatchStyle::Outer => 1,
+        HatchStyle::Ignore => 0,
+    };
+    island_depths(loops)
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, depth)| depth <= max_depth)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Lay a pattern across boundary loops and clip it to the hatched area
+///
+/// `scale` multiplies pattern spacing and dash lengths and `angle` (radians)
+/// rotates the whole pattern about the drawing origin, as for a hatch
+/// entity. Dots come out as zero-length segments.
+pub fn hatch_lines(
+    pattern: &HatchPattern,
+    loops: &[Vec<Point2D>],
+    scale: f64,
+    angle: f64,
+    style: HatchStyle,
+) -> Vec<LineSegment2D> {
+    let mut edges = Vec::new();
+    for i in active_loops(loops, style) {
+        let boundary = &loops[i];
+        if boundary.len() < 3 {
+            continue;
+        }
+        for (k, p) in boundary.iter().enumerate() {
+            edges.push((*p, boundary[(k + 1) % boundary.len()]));
+        }
+    }
+    if edges.is_empty() {
+        return Vec::new();
+    }
+
+    let (min, max) = edges.iter().fold(
+        (
+            Point2D::new(f64::INFINITY, f64::INFINITY),
+            Point2D::new(f64::NEG_INFINITY, f64::NEG_INFINITY),
+        ),
+        |(min, max), (p, _)| {
+            (
+                Point2D::new(min.x.min(p.x), min.y.min(p.y)),
+                Point2D::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        },
+    );
+    let corners = [
+        min,
+        Point2D::new(max.x, min.y),
+        max,
+        Point2D::new(min.x, max.y),
+    ];
+
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+    let mut segments = Vec::new();
+    for family in &pattern.lines {
+        let (sin, cos) = (family.angle + angle).sin_cos();
+        let (dir, normal) = (Point2D::new(cos, sin), Point2D::new(-sin, cos));
+        let spacing = family.offset[1] * scale;
+        if spacing.abs() < crate::core::precision::EPSILON {
+            continue;
+        }
+        let origin = Point2D::new(family.origin[0], family.origin[1]).rotate(angle) * scale;
+        let step = dir * (family.offset[0] * scale) + normal * spacing;
+        let dashes: Vec<f64> = family.dashes.iter().map(|d| d * scale).collect();
+
+        // Rows whose line crosses the boundary's bounding box
+        let across = corners.map(|c| (c - origin).dot(&normal) / spacing);
+        let first = across.iter().copied().fold(f64::INFINITY, f64::min).ceil() as i64;
+        let last = across
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max)
+            .floor() as i64;
+        let rows = (last - first + 1).clamp(0, MAX_ROWS_PER_FAMILY as i64);
+
+        for row in first..first + rows {
+            let row_origin = origin + step * row as f64;
+            let mut hits = Vec::new();
+            for (p, q) in &edges {
+                let dp = (*p - row_origin).dot(&normal);
+                let dq = (*q - row_origin).dot(&normal);
+                // Half-open test so a line through a vertex counts it once
+                if (dp > 0.0) != (dq > 0.0) {
+                    let x = p.lerp(q, dp / (dp - dq));
+                    hits.push((x - row_origin).dot(&dir));
+                }
+            }
+            hits.sort_by(f64::total_cmp);
+            for pair in hits.chunks_exact(2) {
+                lay_dashes(&dashes, pair[0], pair[1], |a, b| {
+                    segments.push(LineSegment2D::new(
+                        row_origin + dir * a,
+                        row_origin + dir * b,
+                    ));
+                });
+            }
+        }
+    }
+    segments
+}
+
+/// Lay a dash pattern, starting at parameter 0, over `[a, b]`
+fn lay_dashes(dashes: &[f64], a: f64, b: f64, mut emit: impl FnMut(f64, f64)) {
+    let period: f64 = dashes.iter().map(|d| d.abs()).sum();
+    if dashes.is_empty() || period <= 0.0 {
+        if b > a {
+            emit(a, b);
+        }
+        return;
+    }
+    let mut t = (a / period).floor() * period;
+    while t < b {
+        for &dash in dashes {
+            if dash > 0.0 {
+                let (lo, hi) = (t.max(a), (t + dash).min(b));
+                if hi > lo {
+                    emit(lo, hi);
+                }
+            } else if dash == 0.0 && t >= a && t < b {
+                emit(t, t);
+            }
+            t += dash.abs();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(min: f64, max: f64) -> Vec<Point2D> {
+        vec![
+            Point2D::new(min, min),
+            Point2D::new(max, min),
+            Point2D::new(max, max),
+            Point2D::new(min, max),
+        ]
+    }
+
+    #[test]
+    fn test_islands() {
+        // Outer square, an island inside it, and a smaller island inside that
+        let loops = vec![square(0.0, 30.0), square(5.0, 25.0), square(10.0, 20.0)];
+        assert_eq!(island_depths(&loops), vec![0, 1, 2]);
+        assert_eq!(active_loops(&loops, HatchStyle::Outer), vec![0, 1]);
+        assert_eq!(active_loops(&loops, HatchStyle::Ignore), vec![0]);
+
+        let pattern = HatchPattern {
+            name: "H".to_string(),
+            description: String::new(),
+            lines: vec![PatternLine {
+                offset: [0.0, 1.0],
+                ..PatternLine::solid(0.0)
+            }],
+        };
+        let total = |style| -> f64 {
+            hatch_lines(&pattern, &loops, 1.0, 0.0, style)
+                .iter()
+                .map(|s| s.length())
+                .sum()
+        };
+
+        // Rows at y = 0..29: 30 full rows ignoring islands
+        assert!((total(HatchStyle::Ignore) - 900.0).abs() < 1e-9);
+        // The first island is left open
+        assert!((total(HatchStyle::Outer) - (900.0 - 20.0 * 20.0)).abs() < 1e-9);
+        // The innermost island is hatched again
+        assert!((total(HatchStyle::Normal) - (900.0 - 400.0 + 100.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_dashed_rotated_pattern() {
+        let pattern = HatchPattern {
+            name: "DASH".to_string(),
+            description: String::new(),
+            lines: vec![PatternLine {
+                dashes: vec![2.0, -1.0, 0.0, -1.0],
+                offset: [0.0, 10.0],
+                ..PatternLine::solid(0.0)
+            }],
+        };
+        // Turned 90 degrees, the single row in range runs up the y axis
+        let loops = vec![square(-5.0, 5.0)];
+        let segments = hatch_lines(
+            &pattern,
+            &loops,
+            1.0,
+            std::f64::consts::FRAC_PI_2,
+            HatchStyle::Normal,
+        );
+
+        assert!(segments
+            .iter()
+            .all(|s| s.start.x.abs() < 1e-9 && s.end.x.abs() < 1e-9));
+        // Period 4 from y = -5 to 5, phased from the origin: dashes at -4,
+        // 0 and 4 (the last cut at 5), dots at -5, -1 and 3
+        let dashes = segments.iter().filter(|s| s.length() > 1e-9).count();
+        let dots = segments.len() - dashes;
+        assert_eq!((dashes, dots), (3, 3));
+        let drawn: f64 = segments.iter().map(|s| s.length()).sum();
+        assert!((drawn - 5.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,417 @@
+//! Convex hulls in 2D and 3D
+//!
+//! Both hulls are deterministic: the same points give the same output in
+//! any input order. Duplicates are merged and points lying on an edge or
+//! face of the hull, within a tolerance scaled to the extent of the input,
+//! are never hull vertices. Degenerate inputs are reported as what they
+//! are rather than as a hull with zero area or volume: a 2D hull of
+//! collinear points is just the two end points, and a 3D hull of coplanar
+//! points is the polygon bounding them.
+
+use crate::core::precision::{EPSILON, EPSILON_ROUGH};
+use crate::geometry::mesh::{TriangleFace, TriangleMesh, Vertex};
+use crate::geometry::point::Point2D;
+use nalgebra::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Convex hull of 2D points
+///
+/// Returns the hull vertices counter-clockwise, starting from the point
+/// with the smallest x (then y). Collinear input gives its two end points,
+/// a single distinct point gives itself and no points give an empty hull.
+pub fn convex_hull_2d(points: &[Point2D]) -> Vec<Point2D> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    let scale = extent(sorted.iter().map(|p| [p.x, p.y]));
+    let tolerance = EPSILON * scale;
+    sorted.dedup_by(|a, b| a.distance_to(b) <= tolerance);
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    // Andrew's monotone chain; a turn counts as left only if it clears the
+    // tolerance, which drops collinear points
+    let turns_left = |o: Point2D, a: Point2D, b: Point2D| {
+        let (u, v) = (a - o, b - o);
+        u.cross(&v) > tolerance * (u.distance_to_origin() + v.distance_to_origin())
+    };
+    let mut hull: Vec<Point2D> = Vec::with_capacity(sorted.len() + 1);
+    let chain = |hull: &mut Vec<Point2D>, floor: usize, p: Point2D| {
+        while hull.len() > floor + 1 && !turns_left(hull[hull.len() - 2], hull[hull.len() - 1], p) {
+            hull.pop();
+        }
+        hull.push(p);
+    };
+    for &p in &sorted {
+        chain(&mut hull, 0, p);
+    }
+    let lower = hull.len() - 1;
+    for &p in sorted.iter().rev().skip(1) {
+        chain(&mut hull, lower, p);
+    }
+    // The upper chain ends back at the first point
+    hull.pop();
+    if hull.len() < 3 {
+        // Collinear: the lower chain went from one end to the other
+        hull.truncate(2);
+    }
+    hull
+}
+
+/// Convex hull of 3D points
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConvexHull3D {
+    /// No points
+    Empty,
+    /// All points coincide
+    Point(Point3<f64>),
+    /// All points are collinear; the two end points
+    Segment(Point3<f64>, Point3<f64>),
+    /// All points are coplanar; the bounding polygon, counter-clockwise
+    /// seen from the side `normal` points to
+    Polygon {
+        /// Vertices
+        vertices: Vec<Point3<f64>>,
+        /// Unit normal of the plane
+        normal: Vector3<f64>,
+    },
+    /// A solid hull
+    Polyhedron {
+        /// Vertices, in ascending x, then y, then z
+        vertices: Vec<Point3<f64>>,
+        /// Triangles, counter-clockwise seen from outside; faces of more
+        /// than three vertices are split into triangles
+        faces: Vec<[usize; 3]>,
+    },
+}
+
+impl ConvexHull3D {
+    /// Hull vertices
+    pub fn vertices(&self) -> Vec<Point3<f64>> {
+        match self {
+            ConvexHull3D::Empty => Vec::new(),
+            ConvexHull3D::Point(p) => vec![*p],
+            ConvexHull3D::Segment(a, b) => vec![*a, *b],
+            ConvexHull3D::Polygon { vertices, .. } | ConvexHull3D::Polyhedron { vertices, .. } => vertices.clone(),
+        }
+    }
+
+    /// Enclosed volume; zero unless the hull is a polyhedron
+    pub fn volume(&self) -> f64 {
+        match self {
+            ConvexHull3D::Polyhedron { vertices, faces } => {
+                let origin = vertices[0].coords;
+                faces
+                    .iter()
+                    .map(|&[a, b, c]| {
+                        let (a, b, c) = (vertices[a].coords - origin, vertices[b].coords - origin, vertices[c].coords - origin);
+                        a.dot(&b.cross(&c))
+                    })
+                    .sum::<f64>()
+                    / 6.0
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Triangle mesh of a polyhedral hull
+    pub fn to_mesh(&self) -> Option<TriangleMesh> {
+        match self {
+            ConvexHull3D::Polyhedron { vertices, faces } => Some(TriangleMesh::from_data(
+                vertices.iter().map(|&p| Vertex::new(p)).collect(),
+                faces.iter().map(|&[a, b, c]| TriangleFace::new(a, b, c)).collect(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Convex hull of 3D points
+///
+/// Builds the hull incrementally from an initial tetrahedron of extreme
+/// points, adding the rest in sorted order, so the result does not depend
+/// on input order.
+pub fn convex_hull_3d(points: &[Point3<f64>]) -> ConvexHull3D {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)).then(a.z.total_cmp(&b.z)));
+    let tolerance = EPSILON * extent(sorted.iter().map(|p| [p.x, p.y, p.z]));
+    sorted.dedup_by(|a, b| (*a - *b).norm() <= tolerance);
+
+    let mut faces = match initial_simplex(&sorted, tolerance) {
+        Ok(simplex) => wrap(&sorted, simplex, tolerance),
+        Err(degenerate) => return degenerate,
+    };
+
+    // A point on an edge or face of the final hull can still become a
+    // vertex if it is added before the points that cover it. Such vertices
+    // have incident faces facing at most two ways; wrapping only the true
+    // corners again leaves them out.
+    let used = vertices_of(&faces);
+    let corners: Vec<usize> = used.iter().copied().filter(|&v| is_corner(&sorted, &faces, v)).collect();
+    if corners.len() < used.len() {
+        sorted = corners.into_iter().map(|i| sorted[i]).collect();
+        faces = match initial_simplex(&sorted, tolerance) {
+            Ok(simplex) => wrap(&sorted, simplex, tolerance),
+            Err(degenerate) => return degenerate,
+        };
+    }
+
+    // Renumber the vertices in use, keeping their sorted order
+    let used = vertices_of(&faces);
+    let renumber = |i: usize| used.binary_search(&i).unwrap_or_default();
+    let mut faces: Vec<[usize; 3]> = faces
+        .into_iter()
+        .map(|face| {
+            let face = face.map(renumber);
+            let start = (0..3).min_by_key(|&k| face[k]).unwrap_or_default();
+            [face[start], face[(start + 1) % 3], face[(start + 2) % 3]]
+        })
+        .collect();
+    faces.sort_unstable();
+
+    ConvexHull3D::Polyhedron {
+        vertices: used.into_iter().map(|i| sorted[i]).collect(),
+        faces,
+    }
+}
+
+/// Initial tetrahedron: the first point, the point farthest from it, the
+/// point farthest from their line and the point farthest from their plane.
+/// Fails with the lower-dimensional hull when there is no such tetrahedron.
+fn initial_simplex(points: &[Point3<f64>], tolerance: f64) -> Result<[usize; 4], ConvexHull3D> {
+    let Some(&first) = points.first() else {
+        return Err(ConvexHull3D::Empty);
+    };
+    let farthest = |cost: &dyn Fn(&Point3<f64>) -> f64| {
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, cost(p)))
+            .fold((0, f64::MIN), |best, next| if next.1 > best.1 { next } else { best })
+    };
+
+    let (i1, d1) = farthest(&|p| (p - first).norm());
+    if d1 <= tolerance {
+        return Err(ConvexHull3D::Point(first));
+    }
+    let axis = (points[i1] - first) / d1;
+    let (i2, d2) = farthest(&|p| (p - first).cross(&axis).norm());
+    if d2 <= tolerance {
+        return Err(ConvexHull3D::Segment(first, points[i1]));
+    }
+    let normal = axis.cross(&(points[i2] - first)).normalize();
+    let (i3, d3) = farthest(&|p| (p - first).dot(&normal).abs());
+    if d3 <= tolerance {
+        return Err(coplanar_hull(points, first, axis, normal));
+    }
+    Ok([0, i1, i2, i3])
+}
+
+/// Outward triangles of the hull grown from `simplex` by adding the other
+/// points in order
+fn wrap(points: &[Point3<f64>], simplex: [usize; 4], tolerance: f64) -> Vec<[usize; 3]> {
+    let [i0, i1, i2, i3] = simplex;
+    let mut faces: Vec<[usize; 3]> = vec![[i0, i1, i2], [i0, i2, i3], [i0, i3, i1], [i1, i3, i2]];
+    let normal = (points[i1] - points[i0]).cross(&(points[i2] - points[i0]));
+    if (points[i3] - points[i0]).dot(&normal) > 0.0 {
+        for face in &mut faces {
+            face.swap(1, 2);
+        }
+    }
+    let outside = |face: &[usize; 3], p: &Point3<f64>| {
+        let [a, b, c] = face.map(|i| points[i]);
+        let n = (b - a).cross(&(c - a));
+        n.dot(&(p - a)) > tolerance * n.norm()
+    };
+
+    for (index, point) in points.iter().enumerate() {
+        if simplex.contains(&index) {
+            continue;
+        }
+        let (visible, kept): (Vec<[usize; 3]>, Vec<[usize; 3]>) = faces.iter().partition(|face| outside(face, point));
+        if visible.is_empty() {
+            continue;
+        }
+        let edges: HashSet<(usize, usize)> = visible
+            .iter()
+            .flat_map(|&[a, b, c]| [(a, b), (b, c), (c, a)])
+            .collect();
+        faces = kept;
+        let mut horizon: Vec<(usize, usize)> = edges.iter().copied().filter(|&(a, b)| !edges.contains(&(b, a))).collect();
+        horizon.sort_unstable();
+        faces.extend(horizon.into_iter().map(|(a, b)| [a, b, index]));
+    }
+    faces
+}
+
+/// Sorted indices of the vertices used by `faces`
+fn vertices_of(faces: &[[usize; 3]]) -> Vec<usize> {
+    let mut used: Vec<usize> = faces.iter().flatten().copied().collect();
+    used.sort_unstable();
+    used.dedup();
+    used
+}
+
+/// Whether the faces around `vertex` face three independent ways, which
+/// is what makes it a corner of the hull rather than a point on one of its
+/// edges or faces
+fn is_corner(points: &[Point3<f64>], faces: &[[usize; 3]], vertex: usize) -> bool {
+    let normals: Vec<Vector3<f64>> = faces
+        .iter()
+        .filter(|face| face.contains(&vertex))
+        .filter_map(|&[a, b, c]| (points[b] - points[a]).cross(&(points[c] - points[a])).try_normalize(0.0))
+        .collect();
+    let Some(first) = normals.first() else {
+        return false;
+    };
+    let Some(edge) = normals.iter().map(|n| first.cross(n)).find(|e| e.norm() > EPSILON_ROUGH) else {
+        return false;
+    };
+    normals.iter().any(|n| edge.normalize().dot(n).abs() > EPSILON_ROUGH)
+}
+
+/// Hull of points lying in the plane through `origin` with unit `normal`,
+/// found in 2D along `axis` and `normal × axis`
+fn coplanar_hull(points: &[Point3<f64>], origin: Point3<f64>, axis: Vector3<f64>, normal: Vector3<f64>) -> ConvexHull3D {
+    let across = normal.cross(&axis);
+    let flat: Vec<Point2D> = points
+        .iter()
+        .map(|p| Point2D::new((p - origin).dot(&axis), (p - origin).dot(&across)))
+        .collect();
+    let vertices = convex_hull_2d(&flat)
+        .into_iter()
+        .map(|q| origin + axis * q.x + across * q.y)
+        .collect();
+    ConvexHull3D::Polygon { vertices, normal }
+}
+
+/// Largest coordinate magnitude or spread, used to scale tolerances
+fn extent<const N: usize>(points: impl Iterator<Item = [f64; N]>) -> f64 {
+    let mut min = [f64::INFINITY; N];
+    let mut max = [f64::NEG_INFINITY; N];
+    for p in points {
+        for k in 0..N {
+            min[k] = min[k].min(p[k]);
+            max[k] = max[k].max(p[k]);
+        }
+    }
+    (0..N)
+        .map(|k| (max[k] - min[k]).max(max[k].abs()).max(min[k].abs()))
+        .fold(1.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p3(x: f64, y: f64, z: f64) -> Point3<f64> {
+        Point3::new(x, y, z)
+    }
+
+    #[test]
+    fn test_hull_2d_drops_interior_and_collinear_points() {
+        let points = vec![
+            Point2D::new(2.0, 0.0),
+            Point2D::new(1.0, 1.0),
+            Point2D::new(0.0, 0.0),
+            Point2D::new(1.0, 0.0), // on an edge
+            Point2D::new(1.0, 0.5), // inside
+            Point2D::new(0.5, 0.5), // on an edge
+            Point2D::new(2.0, 0.0), // repeated
+        ];
+        let hull = convex_hull_2d(&points);
+        assert_eq!(hull, vec![Point2D::new(0.0, 0.0), Point2D::new(2.0, 0.0), Point2D::new(1.0, 1.0)]);
+
+        let mut reversed = points.clone();
+        reversed.reverse();
+        assert_eq!(convex_hull_2d(&reversed), hull);
+    }
+
+    #[test]
+    fn test_hull_2d_degenerate() {
+        assert!(convex_hull_2d(&[]).is_empty());
+        let p = Point2D::new(3.0, 4.0);
+        assert_eq!(convex_hull_2d(&[p, p]), vec![p]);
+
+        let line: Vec<Point2D> = (0..5).map(|i| Point2D::new(i as f64, 2.0 * i as f64)).rev().collect();
+        assert_eq!(convex_hull_2d(&line), vec![Point2D::new(0.0, 0.0), Point2D::new(4.0, 8.0)]);
+    }
+
+    #[test]
+    fn test_hull_3d_cube() {
+        let mut points = Vec::new();
+        for x in 0..3 {
+            for y in 0..3 {
+                for z in 0..3 {
+                    points.push(p3(x as f64, y as f64, z as f64));
+                }
+            }
+        }
+        let hull = convex_hull_3d(&points);
+        let ConvexHull3D::Polyhedron { vertices, faces } = &hull else {
+            panic!("expected a polyhedron");
+        };
+        // Only the corners; face and edge midpoints are dropped
+        assert_eq!(vertices.len(), 8);
+        assert_eq!(faces.len(), 12);
+        assert!((hull.volume() - 8.0).abs() < 1e-9);
+
+        points.reverse();
+        assert_eq!(convex_hull_3d(&points), hull);
+    }
+
+    #[test]
+    fn test_hull_3d_faces_point_outward() {
+        let points: Vec<Point3<f64>> = (0..40)
+            .map(|i| {
+                let (u, v) = (i as f64 * 0.7, i as f64 * 1.3);
+                p3(u.cos() * v.sin(), u.sin() * v.sin(), v.cos())
+            })
+            .chain([p3(0.0, 0.0, 0.0)])
+            .collect();
+        let hull = convex_hull_3d(&points);
+        let ConvexHull3D::Polyhedron { vertices, faces } = &hull else {
+            panic!("expected a polyhedron");
+        };
+        assert!(!vertices.contains(&p3(0.0, 0.0, 0.0)));
+        for &[a, b, c] in faces {
+            let n = (vertices[b] - vertices[a]).cross(&(vertices[c] - vertices[a]));
+            for p in &points {
+                assert!(n.dot(&(p - vertices[a])) <= 1e-9);
+            }
+        }
+        // Closed: every edge is shared by exactly two faces, once each way
+        let edges: HashSet<(usize, usize)> = faces.iter().flat_map(|&[a, b, c]| [(a, b), (b, c), (c, a)]).collect();
+        assert_eq!(edges.len(), faces.len() * 3);
+        assert!(edges.iter().all(|&(a, b)| edges.contains(&(b, a))));
+        assert_eq!(hull.to_mesh().unwrap().faces.len(), faces.len());
+    }
+
+    #[test]
+    fn test_hull_3d_degenerate() {
+        assert_eq!(convex_hull_3d(&[]), ConvexHull3D::Empty);
+        assert_eq!(convex_hull_3d(&[p3(1.0, 1.0, 1.0); 3]), ConvexHull3D::Point(p3(1.0, 1.0, 1.0)));
+        assert_eq!(
+            convex_hull_3d(&[p3(2.0, 2.0, 2.0), p3(0.0, 0.0, 0.0), p3(1.0, 1.0, 1.0)]),
+            ConvexHull3D::Segment(p3(0.0, 0.0, 0.0), p3(2.0, 2.0, 2.0))
+        );
+
+        // A square with its centre, tilted out of the xy plane
+        let square = [
+            p3(0.0, 0.0, 0.0),
+            p3(1.0, 0.0, 1.0),
+            p3(1.0, 1.0, 1.0),
+            p3(0.0, 1.0, 0.0),
+            p3(0.5, 0.5, 0.5),
+        ];
+        let ConvexHull3D::Polygon { vertices, normal } = convex_hull_3d(&square) else {
+            panic!("expected a polygon");
+        };
+        assert_eq!(vertices.len(), 4);
+        assert!((normal.norm() - 1.0).abs() < 1e-12);
+        assert!(normal.dot(&Vector3::new(1.0, 0.0, 1.0)).abs() < 1e-12);
+        let winding = (vertices[1] - vertices[0]).cross(&(vertices[2] - vertices[1]));
+        assert!(winding.dot(&normal) > 0.0);
+    }
+}
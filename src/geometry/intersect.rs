@@ -0,0 +1,1048 @@
+//! Curve-curve intersection
+//!
+//! [`intersect`] finds where two 2D curves meet, for any pair of line
+//! segments, arcs, circles, ellipses, elliptical arcs, parabolas,
+//! hyperbolas, B-splines and NURBS curves. Pairs of lines and circular
+//! curves are solved in closed form. Anything involving another conic or a
+//! spline is bracketed on chords of both
+//! curves and then polished with damped Newton iteration on the curves
+//! themselves, so the points lie on both to within the tolerance rather than
+//! the chords' sag.
+//!
+//! Near a tangency the curves run within tolerance of each other over a
+//! short stretch, and a solver may see no root there, one, or two almost
+//! coincident ones depending on rounding. Roots with the curves still within
+//! tolerance of each other halfway between them are merged into a single
+//! [`IntersectionKind::Tangent`] point, and curves that touch without
+//! crossing are reported the same way. Crossings at an angle below
+//! √tolerance radians count as tangent too: the curves then stay within
+//! tolerance of each other for about √tolerance either side of the point.
+//!
+//! Collinear segments and arcs on the same circle report the ends of their
+//! shared stretch as [`IntersectionKind::Overlap`]. Coincident full circles
+//! have no ends to report and give nothing; coincident ellipses and splines
+//! are not recognised as overlaps.
+//!
+//! With a [`Tolerance`] in [`PredicateMode::Exact`], two segments whose ends
+//! lie strictly on opposite sides of each other are always reported as
+//! meeting, however close to parallel they are; in the default tolerance
+//! mode such a pair is dropped once their directions are parallel to within
+//! [`EPSILON`].
+
+use crate::core::precision::{orient2d, PredicateMode, Tolerance, EPSILON};
+use crate::geometry::arc::{Arc2D, Circle2D, Ellipse2D, EllipticalArc2D};
+use crate::geometry::conic::{Hyperbola2D, Parabola2D};
+use crate::geometry::curve::{BSpline, NurbsCurve};
+use crate::geometry::fillet::sweep_to;
+use crate::geometry::line::LineSegment2D;
+use crate::geometry::point::Point2D;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::{PI, TAU};
+
+/// Chords per knot span used to bracket roots on splines
+const CHORDS_PER_SPAN: usize = 16;
+
+/// Angle turned per chord on circles and ellipses
+const CHORD_ANGLE: f64 = PI / 32.0;
+
+/// Newton iterations per root
+const MAX_ITERATIONS: usize = 100;
+
+/// A curve to intersect, borrowed
+///
+/// Parameters are a fraction along segments, the angle for circles and the
+/// ellipse's angle parameter for ellipses, the angle swept from the start
+/// for arcs and elliptical arcs, the distance of the curve's own parameter
+/// from its start for parabolas and hyperbolas, and the knot parameter for
+/// splines.
+#[derive(Debug, Clone, Copy)]
+pub enum CurveRef<'a> {
+    Segment(&'a LineSegment2D),
+    Arc(&'a Arc2D),
+    Circle(&'a Circle2D),
+    Ellipse(&'a Ellipse2D),
+    EllipticalArc(&'a EllipticalArc2D),
+    Parabola(&'a Parabola2D),
+    Hyperbola(&'a Hyperbola2D),
+    BSpline(&'a BSpline),
+    Nurbs(&'a NurbsCurve),
+}
+
+impl<'a> From<&'a LineSegment2D> for CurveRef<'a> {
+    fn from(curve: &'a LineSegment2D) -> Self {
+        CurveRef::Segment(curve)
+    }
+}
+
+impl<'a> From<&'a Arc2D> for CurveRef<'a> {
+    fn from(curve: &'a Arc2D) -> Self {
+        CurveRef::Arc(curve)
+    }
+}
+
+impl<'a> From<&'a Circle2D> for CurveRef<'a> {
+    fn from(curve: &'a Circle2D) -> Self {
+        CurveRef::Circle(curve)
+    }
+}
+
+impl<'a> From<&'a Ellipse2D> for CurveRef<'a> {
+    fn from(curve: &'a Ellipse2D) -> Self {
+        CurveRef::Ellipse(curve)
+    }
+}
+
+impl<'a> From<&'a EllipticalArc2D> for CurveRef<'a> {
+    fn from(curve: &'a EllipticalArc2D) -> Self {
+        CurveRef::EllipticalArc(curve)
+    }
+}
+
+impl<'a> From<&'a Parabola2D> for CurveRef<'a> {
+    fn from(curve: &'a Parabola2D) -> Self {
+        CurveRef::Parabola(curve)
+    }
+}
+
+impl<'a> From<&'a Hyperbola2D> for CurveRef<'a> {
+    fn from(curve: &'a Hyperbola2D) -> Self {
+        CurveRef::Hyperbola(curve)
+    }
+}
+
+impl<'a> From<&'a BSpline> for CurveRef<'a> {
+    fn from(curve: &'a BSpline) -> Self {
+        CurveRef::BSpline(curve)
+    }
+}
+
+impl<'a> From<&'a NurbsCurve> for CurveRef<'a> {
+    fn from(curve: &'a NurbsCurve) -> Self {
+        CurveRef::Nurbs(curve)
+    }
+}
+
+/// How two curves meet at an intersection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IntersectionKind {
+    /// The curves cross
+    Crossing,
+    /// The curves touch, or cross at a grazing angle
+    Tangent,
+    /// An end of a stretch the curves share
+    Overlap,
+}
+
+/// A point where two curves meet
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Intersection {
+    /// The point, halfway between where the two parameters put it
+    pub point: Point2D,
+    /// Parameter on the first curve
+    pub param_a: f64,
+    /// Parameter on the second curve
+    pub param_b: f64,
+    pub kind: IntersectionKind,
+}
+
+/// Intersect two curves to within [`EPSILON`], in order along the first
+pub fn intersect<'a, 'b>(a: impl Into<CurveRef<'a>>, b: impl Into<CurveRef<'b>>) -> Vec<Intersection> {
+    intersect_with_tolerance(a, b, EPSILON)
+}
+
+/// Intersect two curves, treating points within `tolerance` as on both
+pub fn intersect_with_tolerance<'a, 'b>(
+    a: impl Into<CurveRef<'a>>,
+    b: impl Into<CurveRef<'b>>,
+    tolerance: f64,
+) -> Vec<Intersection> {
+    let tolerance = Tolerance {
+        distance: tolerance,
+        ..Tolerance::default()
+    };
+    intersect_within(a, b, &tolerance)
+}
+
+/// Intersect two curves within a tolerance's distance, deciding segment
+/// crossings with its [`PredicateMode`]
+pub fn intersect_within<'a, 'b>(
+    a: impl Into<CurveRef<'a>>,
+    b: impl Into<CurveRef<'b>>,
+    tolerance: &Tolerance,
+) -> Vec<Intersection> {
+    let (a, b) = (a.into(), b.into());
+    let predicates = tolerance.predicates;
+    let tolerance = tolerance.distance.max(f64::EPSILON);
+    let roots = match closed_form(&a, &b, tolerance, predicates) {
+        Some(roots) => roots,
+        None => bracket(&a, &b, tolerance),
+    };
+    finish(&a, &b, roots, tolerance)
+}
+
+impl CurveRef<'_> {
+    /// Parameter domain
+    pub fn domain(&self) -> (f64, f64) {
+        match self {
+            CurveRef::Segment(_) => (0.0, 1.0),
+            CurveRef::Arc(arc) => (0.0, arc.sweep_angle()),
+            CurveRef::Circle(_) | CurveRef::Ellipse(_) => (0.0, TAU),
+            CurveRef::EllipticalArc(arc) => (0.0, arc.sweep_angle()),
+            CurveRef::Parabola(p) => (0.0, (p.end - p.start).abs()),
+            CurveRef::Hyperbola(h) => (0.0, (h.end - h.start).abs()),
+            CurveRef::BSpline(spline) => spline.parameter_range(),
+            CurveRef::Nurbs(nurbs) => nurbs.parameter_range(),
+        }
+    }
+
+    /// Point at parameter `t`
+    pub fn point_at(&self, t: f64) -> Point2D {
+        match self {
+            CurveRef::Segment(line) => line.start + (line.end - line.start) * t,
+            CurveRef::Arc(arc) => arc.center + Point2D::from_polar(arc.radius, arc_angle(arc.start_angle, arc.ccw, t)),
+            CurveRef::Circle(circle) => circle.center + Point2D::from_polar(circle.radius, t),
+            CurveRef::Ellipse(ellipse) => ellipse.point_at_angle(t),
+            CurveRef::EllipticalArc(arc) => arc.point_at_angle(arc_angle(arc.start_angle, arc.ccw, t)),
+            CurveRef::Parabola(p) => p.point_at(own_param(p.start, p.end, t)),
+            CurveRef::Hyperbola(h) => h.point_at(own_param(h.start, h.end, t)),
+            CurveRef::BSpline(spline) => spline.evaluate(t),
+            CurveRef::Nurbs(nurbs) => nurbs.evaluate(t),
+        }
+    }
+
+    /// Derivative with respect to the parameter at `t`
+    pub fn derivative_at(&self, t: f64) -> Point2D {
+        let turn = |ccw: bool| if ccw { 1.0 } else { -1.0 };
+        match self {
+            CurveRef::Segment(line) => line.end - line.start,
+            CurveRef::Arc(arc) => {
+                let angle = arc_angle(arc.start_angle, arc.ccw, t);
+                Point2D::new(-angle.sin(), angle.cos()) * (arc.radius * turn(arc.ccw))
+            }
+            CurveRef::Circle(circle) => Point2D::new(-t.sin(), t.cos()) * circle.radius,
+            CurveRef::Ellipse(e) => ellipse_derivative(e.semi_major, e.semi_minor, e.rotation, t),
+            CurveRef::EllipticalArc(arc) => {
+                let angle = arc_angle(arc.start_angle, arc.ccw, t);
+                ellipse_derivative(arc.semi_major, arc.semi_minor, arc.rotation, angle) * turn(arc.ccw)
+            }
+            CurveRef::Parabola(p) => p.derivative_at(own_param(p.start, p.end, t)) * turn(p.end >= p.start),
+            CurveRef::Hyperbola(h) => h.derivative_at(own_param(h.start, h.end, t)) * turn(h.end >= h.start),
+            CurveRef::BSpline(_) | CurveRef::Nurbs(_) => {
+                let (lo, hi) = self.domain();
+                let h = (hi - lo) * 1e-7;
+                let (t0, t1) = ((t - h).max(lo), (t + h).min(hi));
+                (self.point_at(t1) - self.point_at(t0)) / (t1 - t0)
+            }
+        }
+    }
+
+    /// Parameters splitting the curve into smooth pieces that turn at most a
+    /// quarter turn each, for conics, or into knot spans, for splines
+    pub(crate) fn breaks(&self) -> Vec<f64> {
+        let (lo, hi) = self.domain();
+        let distinct = |knots: &[f64]| {
+            let mut params = vec![lo];
+            params.extend(knots.iter().copied().filter(|&k| k > lo && k < hi));
+            params.push(hi);
+            params.dedup();
+            params
+        };
+        match self {
+            CurveRef::Segment(_) => vec![lo, hi],
+            CurveRef::BSpline(spline) => distinct(&spline.knots),
+            CurveRef::Nurbs(nurbs) => distinct(&nurbs.knots),
+            CurveRef::Parabola(_) | CurveRef::Hyperbola(_) => self.turning_params(PI / 2.0),
+            _ => {
+                let count = ((hi - lo) / (PI / 2.0) - EPSILON).ceil().max(1.0) as usize;
+                (0..=count).map(|i| lo + (hi - lo) * i as f64 / count as f64).collect()
+            }
+        }
+    }
+
+    /// Whether the parameter wraps around
+    fn is_periodic(&self) -> bool {
+        matches!(self, CurveRef::Circle(_) | CurveRef::Ellipse(_))
+    }
+
+    fn is_straight(&self) -> bool {
+        matches!(self, CurveRef::Segment(_))
+    }
+
+    /// Keep `t` in the domain, wrapping periodic curves
+    fn wrap(&self, t: f64) -> f64 {
+        let (lo, hi) = self.domain();
+        if self.is_periodic() {
+            lo + (t - lo).rem_euclid(hi - lo)
+        } else {
+            t.clamp(lo, hi)
+        }
+    }
+
+    /// Parameters of the chord ends roots are bracketed on
+    pub(crate) fn samples(&self) -> Vec<f64> {
+        let (lo, hi) = self.domain();
+        let even = |count: usize| -> Vec<f64> {
+            (0..=count).map(|i| lo + (hi - lo) * i as f64 / count as f64).collect()
+        };
+        let knot_spans = |knots: &[f64]| {
+            let mut params = Vec::new();
+            for span in knots.windows(2) {
+                let (a, b) = (span[0].max(lo), span[1].min(hi));
+                if b > a {
+                    params.extend((0..CHORDS_PER_SPAN).map(|i| a + (b - a) * i as f64 / CHORDS_PER_SPAN as f64));
+                }
+            }
+            params.push(hi);
+            params
+        };
+        match self {
+            CurveRef::Segment(_) => vec![lo, hi],
+            CurveRef::BSpline(spline) => knot_spans(&spline.knots),
+            CurveRef::Nurbs(nurbs) => knot_spans(&nurbs.knots),
+            CurveRef::Parabola(_) | CurveRef::Hyperbola(_) => self.turning_params(CHORD_ANGLE),
+            _ => even(((hi - lo) / CHORD_ANGLE).ceil().max(1.0) as usize),
+        }
+    }
+
+    /// Parameters splitting a parabola or hyperbola into pieces whose
+    /// tangent turns through equal angles of at most `max_turn`
+    ///
+    /// Even steps in the curve's own parameter would crowd the pieces into
+    /// its flat far reaches and leave the bend near the vertex too coarse.
+    fn turning_params(&self, max_turn: f64) -> Vec<f64> {
+        let (start, end) = match self {
+            CurveRef::Parabola(p) => (p.start, p.end),
+            CurveRef::Hyperbola(h) => (h.start, h.end),
+            _ => return vec![self.domain().0, self.domain().1],
+        };
+        let (from, to) = (self.tangent_angle(start), self.tangent_angle(end));
+        let count = ((to - from).abs() / max_turn - EPSILON).ceil().max(1.0) as usize;
+        let span = (end - start).abs();
+        let mut params: Vec<f64> = (0..=count)
+            .map(|i| {
+                let t = self.at_tangent_angle(from + (to - from) * i as f64 / count as f64);
+                (t - start).abs().min(span)
+            })
+            .collect();
+        params[0] = 0.0;
+        params[count] = span;
+        params
+    }
+
+    /// Angle of a parabola's or hyperbola's tangent from the normal to its
+    /// axis at its own parameter `t`; it grows with `t`
+    fn tangent_angle(&self, t: f64) -> f64 {
+        match self {
+            CurveRef::Hyperbola(h) => (h.semi_major / h.semi_minor * t.tanh()).atan(),
+            _ => t.atan(),
+        }
+    }
+
+    /// Inverse of [`tangent_angle`](Self::tangent_angle)
+    fn at_tangent_angle(&self, angle: f64) -> f64 {
+        match self {
+            CurveRef::Hyperbola(h) => (angle.tan() * h.semi_minor / h.semi_major).clamp(-1.0, 1.0).atanh(),
+            _ => angle.tan(),
+        }
+    }
+}
+
+/// The curve's own parameter `t` from `start` towards `end`
+fn own_param(start: f64, end: f64, t: f64) -> f64 {
+    if end >= start {
+        start + t
+    } else {
+        start - t
+    }
+}
+
+/// Angle reached `t` radians from `start` in the arc's direction
+fn arc_angle(start: f64, ccw: bool, t: f64) -> f64 {
+    if ccw {
+        start + t
+    } else {
+        start - t
+    }
+}
+
+fn ellipse_derivative(semi_major: f64, semi_minor: f64, rotation: f64, angle: f64) -> Point2D {
+    Point2D::new(-semi_major * angle.sin(), semi_minor * angle.cos()).rotate(rotation)
+}
+
+/// Parameters of a candidate root
+#[derive(Debug, Clone, Copy)]
+struct Root {
+    s: f64,
+    t: f64,
+    tangent: bool,
+    overlap: bool,
+}
+
+impl Root {
+    fn new(s: f64, t: f64) -> Self {
+        Self {
+            s,
+            t,
+            tangent: false,
+            overlap: false,
+        }
+    }
+
+    fn tangent(mut self, tangent: bool) -> Self {
+        self.tangent = tangent;
+        self
+    }
+}
+
+/// A circle or arc as centre and radius
+fn circle_of(curve: &CurveRef) -> Option<(Point2D, f64)> {
+    match curve {
+        CurveRef::Arc(arc) => Some((arc.center, arc.radius)),
+        CurveRef::Circle(circle) => Some((circle.center, circle.radius)),
+        _ => None,
+    }
+}
+
+/// Parameter of a point at most `tolerance` outside a segment or circular curve
+fn param_of(curve: &CurveRef, p: Point2D, tolerance: f64) -> Option<f64> {
+    match curve {
+        CurveRef::Segment(line) => {
+            let d = line.end - line.start;
+            let length = d.distance_to_origin();
+            let f = (p - line.start).dot(&d) / (length * length);
+            let slack = tolerance / length;
+            (f >= -slack && f <= 1.0 + slack).then(|| f.clamp(0.0, 1.0))
+        }
+        CurveRef::Arc(arc) => {
+            let slack = tolerance / arc.radius;
+            let swept = sweep_to(arc, arc.center.angle_to(&p));
+            if swept > TAU - slack {
+                Some(0.0)
+            } else if swept > arc.sweep_angle() + slack {
+                None
+            } else {
+                Some(swept.min(arc.sweep_angle()))
+            }
+        }
+        CurveRef::Circle(circle) => Some(circle.center.angle_to(&p).rem_euclid(TAU)),
+        _ => None,
+    }
+}
+
+/// Roots of pairs with closed-form solutions; `None` for the others
+fn closed_form(a: &CurveRef, b: &CurveRef, tolerance: f64, predicates: PredicateMode) -> Option<Vec<Root>> {
+    let on_both = |points: Vec<(Point2D, bool)>| -> Vec<Root> {
+        points
+            .into_iter()
+            .filter_map(|(p, tangent)| {
+                Some(Root::new(param_of(a, p, tolerance)?, param_of(b, p, tolerance)?).tangent(tangent))
+            })
+            .collect()
+    };
+
+    match (a, b) {
+        (CurveRef::Segment(p), CurveRef::Segment(q)) => Some(segment_segment(p, q, tolerance, predicates)),
+        (CurveRef::Segment(line), other) | (other, CurveRef::Segment(line)) => {
+            let (center, radius) = circle_of(other)?;
+            Some(on_both(line_circle(line, center, radius, tolerance)))
+        }
+        _ => {
+            let (c1, r1) = circle_of(a)?;
+            let (c2, r2) = circle_of(b)?;
+            if c1.distance_to(&c2) <= tolerance && (r1 - r2).abs() <= tolerance {
+                return Some(coincident_circles(a, b, tolerance));
+            }
+            Some(on_both(circle_circle(c1, r1, c2, r2, tolerance)))
+        }
+    }
+}
+
+fn segment_segment(
+    p: &LineSegment2D,
+    q: &LineSegment2D,
+    tolerance: f64,
+    predicates: PredicateMode,
+) -> Vec<Root> {
+    let (d1, d2) = (p.end - p.start, q.end - q.start);
+    let (l1, l2) = (d1.distance_to_origin(), d2.distance_to_origin());
+    if l1 < EPSILON || l2 < EPSILON {
+        return Vec::new();
+    }
+
+    // Both ends of q within tolerance of p's line: collinear
+    let off_line = |point: Point2D| (d1.cross(&(point - p.start)) / l1).abs();
+    if off_line(q.start) <= tolerance && off_line(q.end) <= tolerance {
+        let along = |point: Point2D| (point - p.start).dot(&d1) / (l1 * l1);
+        let (s0, s1) = (along(q.start), along(q.end));
+        let (lo, hi) = (s0.min(s1).max(0.0), s0.max(s1).min(1.0));
+        let back = |s: f64| ((p.start + d1 * s - q.start).dot(&d2) / (l2 * l2)).clamp(0.0, 1.0);
+        if (hi - lo) * l1 < -tolerance {
+            return Vec::new();
+        }
+        if (hi - lo) * l1 <= tolerance {
+            let s = ((lo + hi) / 2.0).clamp(0.0, 1.0);
+            return vec![Root::new(s, back(s)).tangent(true)];
+        }
+        return [lo, hi]
+            .into_iter()
+            .map(|s| Root {
+                overlap: true,
+                ..Root::new(s, back(s))
+            })
+            .collect();
+    }
+
+    let denom = d1.cross(&d2);
+    let crosses = predicates == PredicateMode::Exact && segments_cross(p, q);
+    if denom == 0.0 || (denom.abs() < EPSILON * l1 * l2 && !crosses) {
+        return Vec::new();
+    }
+    let w = q.start - p.start;
+    let s = w.cross(&d2) / denom;
+    let t = w.cross(&d1) / denom;
+    let (slack_s, slack_t) = (tolerance / l1, tolerance / l2);
+    if !crosses && (s < -slack_s || s > 1.0 + slack_s || t < -slack_t || t > 1.0 + slack_t) {
+        return Vec::new();
+    }
+    vec![Root::new(s.clamp(0.0, 1.0), t.clamp(0.0, 1.0))]
+}
+
+/// Whether each segment's ends lie strictly on opposite sides of the other,
+/// decided exactly
+fn segments_cross(p: &LineSegment2D, q: &LineSegment2D) -> bool {
+    let side = |a: Point2D, b: Point2D, c: Point2D| orient2d([a.x, a.y], [b.x, b.y], [c.x, c.y]);
+    let opposite = |x: f64, y: f64| (x > 0.0 && y < 0.0) || (x < 0.0 && y > 0.0);
+    opposite(side(p.start, p.end, q.start), side(p.start, p.end, q.end))
+        && opposite(side(q.start, q.end, p.start), side(q.start, q.end, p.end))
+}
+
+/// Points where the segment's line meets a circle, flagged if tangent
+fn line_circle(line: &LineSegment2D, center: Point2D, radius: f64, tolerance: f64) -> Vec<(Point2D, bool)> {
+    let d = line.end - line.start;
+    if d.distance_to_origin() < EPSILON {
+        return Vec::new();
+    }
+    let u = d.normalize();
+    let foot = line.start + u * (center - line.start).dot(&u);
+    let gap = foot.distance_to(&center);
+    if gap > radius + tolerance {
+        return Vec::new();
+    }
+    if (gap - radius).abs() <= tolerance {
+        return vec![(foot, true)];
+    }
+    let half = (radius * radius - gap * gap).sqrt();
+    vec![(foot - u * half, false), (foot + u * half, false)]
+}
+
+/// Points where two circles meet, flagged if tangent
+fn circle_circle(c1: Point2D, r1: f64, c2: Point2D, r2: f64, tolerance: f64) -> Vec<(Point2D, bool)> {
+    let d = c1.distance_to(&c2);
+    if d <= tolerance || d > r1 + r2 + tolerance || d < (r1 - r2).abs() - tolerance {
+        return Vec::new();
+    }
+    let u = (c2 - c1) / d;
+    let along = ((r1 * r1 - r2 * r2 + d * d) / (2.0 * d)).clamp(-r1, r1);
+    let base = c1 + u * along;
+    if (d - (r1 + r2)).abs() <= tolerance || (d - (r1 - r2).abs()).abs() <= tolerance {
+        return vec![(base, true)];
+    }
+    let half = (r1 * r1 - along * along).max(0.0).sqrt();
+    let n = Point2D::new(-u.y, u.x);
+    vec![(base + n * half, false), (base - n * half, false)]
+}
+
+/// Ends of the stretches shared by two circular curves on the same circle
+fn coincident_circles(a: &CurveRef, b: &CurveRef, tolerance: f64) -> Vec<Root> {
+    // Each curve as a counterclockwise angle interval
+    let interval = |curve: &CurveRef| match curve {
+        CurveRef::Arc(arc) => {
+            let from = if arc.ccw { arc.start_angle } else { arc.end_angle };
+            Some((from, from + arc.sweep_angle()))
+        }
+        _ => None,
+    };
+    let (center, radius) = circle_of(a).expect("circular curve");
+    let (ia, ib) = match (interval(a), interval(b)) {
+        (None, None) => return Vec::new(),
+        (Some(i), None) | (None, Some(i)) => (i, i),
+        (Some(ia), Some(ib)) => (ia, ib),
+    };
+
+    let at = |angle: f64| center + Point2D::from_polar(radius, angle);
+    let root = |angle: f64| Some(Root::new(param_of(a, at(angle), tolerance)?, param_of(b, at(angle), tolerance)?));
+    let mut roots = Vec::new();
+    for shift in [-TAU, 0.0, TAU] {
+        let (lo, hi) = (ia.0.max(ib.0 + shift), ia.1.min(ib.1 + shift));
+        let length = (hi - lo) * radius;
+        if length < -tolerance {
+            continue;
+        }
+        if length <= tolerance {
+            roots.extend(root((lo + hi) / 2.0).map(|r| r.tangent(true)));
+        } else {
+            for angle in [lo, hi] {
+                roots.extend(root(angle).map(|r| Root { overlap: true, ..r }));
+            }
+        }
+    }
+    roots
+}
+
+/// Roots found by polishing from every pair of nearby chords
+fn bracket(a: &CurveRef, b: &CurveRef, tolerance: f64) -> Vec<Root> {
+    let chords = |curve: &CurveRef| -> Vec<Chord> {
+        let params = curve.samples();
+        params
+            .windows(2)
+            .map(|w| Chord::new(curve, w[0], w[1], tolerance))
+            .collect()
+    };
+    let (chords_a, chords_b) = (chords(a), chords(b));
+
+    let mut roots = Vec::new();
+    for ca in &chords_a {
+        for cb in chords_b.iter().filter(|cb| ca.overlaps(cb)) {
+            let (u, v) = closest_on_segments(ca.from, ca.to, cb.from, cb.to);
+            let s = ca.s0 + (ca.s1 - ca.s0) * u;
+            let t = cb.s0 + (cb.s1 - cb.s0) * v;
+            let (s, t, gap) = polish(a, b, s, t);
+            if gap <= tolerance {
+                roots.push(Root::new(s, t));
+            }
+        }
+    }
+    roots
+}
+
+/// A chord of a curve with a box that also covers the arc it cuts off
+struct Chord {
+    s0: f64,
+    s1: f64,
+    from: Point2D,
+    to: Point2D,
+    min: Point2D,
+    max: Point2D,
+}
+
+impl Chord {
+    fn new(curve: &CurveRef, s0: f64, s1: f64, tolerance: f64) -> Self {
+        let (from, to) = (curve.point_at(s0), curve.point_at(s1));
+        // Sampling keeps the turn along each chord well under a half-turn,
+        // where the sag is at most half the chord
+        let sag = if curve.is_straight() {
+            0.0
+        } else {
+            from.distance_to(&to) / 2.0
+        };
+        let margin = sag + tolerance;
+        Self {
+            s0,
+            s1,
+            from,
+            to,
+            min: Point2D::new(from.x.min(to.x) - margin, from.y.min(to.y) - margin),
+            max: Point2D::new(from.x.max(to.x) + margin, from.y.max(to.y) + margin),
+        }
+    }
+
+    fn overlaps(&self, other: &Chord) -> bool {
+        self.min.x <= other.max.x && other.min.x <= self.max.x && self.min.y <= other.max.y && other.min.y <= self.max.y
+    }
+}
+
+/// Fractions along two segments of their closest points
+fn closest_on_segments(p0: Point2D, p1: Point2D, q0: Point2D, q1: Point2D) -> (f64, f64) {
+    let (d1, d2, r) = (p1 - p0, q1 - q0, p0 - q0);
+    let (a, e, f) = (d1.dot(&d1), d2.dot(&d2), d2.dot(&r));
+    if a < EPSILON * EPSILON && e < EPSILON * EPSILON {
+        return (0.0, 0.0);
+    }
+    if a < EPSILON * EPSILON {
+        return (0.0, (f / e).clamp(0.0, 1.0));
+    }
+    let c = d1.dot(&r);
+    if e < EPSILON * EPSILON {
+        return ((-c / a).clamp(0.0, 1.0), 0.0);
+    }
+    let b = d1.dot(&d2);
+    let denom = a * e - b * b;
+    let mut s = if denom > EPSILON * a * e {
+        ((b * f - c * e) / denom).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let mut t = (b * s + f) / e;
+    if t < 0.0 {
+        t = 0.0;
+        s = (-c / a).clamp(0.0, 1.0);
+    } else if t > 1.0 {
+        t = 1.0;
+        s = ((b - c) / a).clamp(0.0, 1.0);
+    }
+    (s, t)
+}
+
+/// Minimise the distance between `a(s)` and `b(t)` from a starting guess
+///
+/// Levenberg-Marquardt: plain Newton converges quadratically at a crossing,
+/// while the damping keeps it stable at a tangency, where the Jacobian is
+/// singular. Returns the parameters and the remaining gap.
+fn polish(a: &CurveRef, b: &CurveRef, mut s: f64, mut t: f64) -> (f64, f64, f64) {
+    let residual = |s: f64, t: f64| a.point_at(s) - b.point_at(t);
+    let mut f = residual(s, t);
+    let mut err = f.dot(&f);
+    let mut lambda = 1e-3;
+
+    for _ in 0..MAX_ITERATIONS {
+        if err == 0.0 {
+            break;
+        }
+        let (da, db) = (a.derivative_at(s), -b.derivative_at(t));
+        let (m11, m12, m22) = (da.dot(&da), da.dot(&db), db.dot(&db));
+        let (g1, g2) = (da.dot(&f), db.dot(&f));
+
+        let mut improved = false;
+        while lambda < 1e12 {
+            let (a11, a22) = (m11 * (1.0 + lambda) + 1e-300, m22 * (1.0 + lambda) + 1e-300);
+            let det = a11 * a22 - m12 * m12;
+            if det > 0.0 && det.is_finite() {
+                let ds = -(a22 * g1 - m12 * g2) / det;
+                let dt = -(a11 * g2 - m12 * g1) / det;
+                let (ns, nt) = (a.wrap(s + ds), b.wrap(t + dt));
+                let nf = residual(ns, nt);
+                let nerr = nf.dot(&nf);
+                if nerr < err {
+                    (s, t, f, err) = (ns, nt, nf, nerr);
+                    lambda = (lambda / 10.0).max(1e-15);
+                    improved = true;
+                    break;
+                }
+            }
+            lambda *= 10.0;
+        }
+        if !improved {
+            break;
+        }
+    }
+    (s, t, err.sqrt())
+}
+
+/// Gap between the curves at `s` on the first, searching the second from `t`
+fn gap_at(a: &CurveRef, b: &CurveRef, s: f64, t: f64) -> f64 {
+    let target = a.point_at(s);
+    let mut t = t;
+    let mut best = target.distance_to(&b.point_at(t));
+    for _ in 0..MAX_ITERATIONS {
+        let d = b.derivative_at(t);
+        let speed = d.dot(&d);
+        if speed < EPSILON * EPSILON {
+            break;
+        }
+        let next = b.wrap(t + (target - b.point_at(t)).dot(&d) / speed);
+        let gap = target.distance_to(&b.point_at(next));
+        if gap >= best {
+            break;
+        }
+        (t, best) = (next, gap);
+    }
+    best
+}
+
+/// Whether two roots are the same contact: the same point, or the ends of a
+/// stretch where the curves stay within tolerance (a near-tangency)
+fn same_contact(a: &CurveRef, b: &CurveRef, r1: &Root, r2: &Root, tolerance: f64) -> bool {
+    let p1 = a.point_at(r1.s);
+    let p2 = a.point_at(r2.s);
+    if p1.distance_to(&p2) <= tolerance {
+        return true;
+    }
+    if r1.overlap || r2.overlap {
+        return false;
+    }
+    let mut dt = r2.t - r1.t;
+    if b.is_periodic() {
+        dt = (dt + PI).rem_euclid(TAU) - PI;
+    }
+    gap_at(a, b, (r1.s + r2.s) / 2.0, r1.t + dt / 2.0) <= tolerance
+}
+
+/// Merge duplicate and near-tangent roots and classify what is left
+fn finish(a: &CurveRef, b: &CurveRef, mut roots: Vec<Root>, tolerance: f64) -> Vec<Intersection> {
+    for root in &mut roots {
+        root.s = a.wrap(root.s);
+        root.t = b.wrap(root.t);
+    }
+    roots.sort_by(|x, y| x.s.total_cmp(&y.s));
+
+    let mut merged: Vec<Root> = Vec::with_capacity(roots.len());
+    for root in roots {
+        match merged.last_mut() {
+            Some(last) if same_contact(a, b, last, &root, tolerance) => *last = combine(a, b, last, &root, tolerance),
+            _ => merged.push(root),
+        }
+    }
+    // The last root may be the first one again, seen from the other end of
+    // a periodic parameter
+    if merged.len() > 1 && a.is_periodic() {
+        let (first, last) = (merged[0], merged[merged.len() - 1]);
+        let wrapped = Root {
+            s: first.s + TAU,
+            ..first
+        };
+        if same_contact(a, b, &last, &wrapped, tolerance) {
+            merged.pop();
+            merged[0] = combine(a, b, &last, &wrapped, tolerance);
+        }
+    }
+
+    let grazing = tolerance.sqrt();
+    merged
+        .into_iter()
+        .map(|root| {
+            let (s, t) = (a.wrap(root.s), b.wrap(root.t));
+            let kind = if root.overlap {
+                IntersectionKind::Overlap
+            } else if root.tangent || sine_between(a.derivative_at(s), b.derivative_at(t)) <= grazing {
+                IntersectionKind::Tangent
+            } else {
+                IntersectionKind::Crossing
+            };
+            Intersection {
+                point: a.point_at(s).midpoint(&b.point_at(t)),
+                param_a: s,
+                param_b: t,
+                kind,
+            }
+        })
+        .collect()
+}
+
+/// One root standing for two that are the same contact
+fn combine(a: &CurveRef, b: &CurveRef, r1: &Root, r2: &Root, tolerance: f64) -> Root {
+    if a.point_at(r1.s).distance_to(&a.point_at(r2.s)) <= tolerance {
+        return Root {
+            tangent: r1.tangent || r2.tangent,
+            overlap: r1.overlap || r2.overlap,
+            ..*r1
+        };
+    }
+    let mut dt = r2.t - r1.t;
+    if b.is_periodic() {
+        dt = (dt + PI).rem_euclid(TAU) - PI;
+    }
+    let (s, t) = ((r1.s + r2.s) / 2.0, r1.t + dt / 2.0);
+    let (ps, pt, gap) = polish(a, b, s, t);
+    let (s, t) = if gap <= tolerance { (ps, pt) } else { (s, t) };
+    Root::new(s, t).tangent(true)
+}
+
+/// |sin| of the angle between two directions; 1 if either is degenerate
+fn sine_between(u: Point2D, v: Point2D) -> f64 {
+    let norms = u.distance_to_origin() * v.distance_to_origin();
+    if norms < EPSILON * EPSILON {
+        return 1.0;
+    }
+    (u.cross(&v) / norms).abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOL: f64 = 1e-9;
+
+    fn segment(x1: f64, y1: f64, x2: f64, y2: f64) -> LineSegment2D {
+        LineSegment2D::new(Point2D::new(x1, y1), Point2D::new(x2, y2))
+    }
+
+    fn kinds(hits: &[Intersection]) -> Vec<IntersectionKind> {
+        hits.iter().map(|h| h.kind).collect()
+    }
+
+    /// Every hit lies on both curves at its parameters
+    fn assert_on_both<'a, 'b>(a: impl Into<CurveRef<'a>>, b: impl Into<CurveRef<'b>>, hits: &[Intersection]) {
+        let (a, b) = (a.into(), b.into());
+        for hit in hits {
+            assert!(a.point_at(hit.param_a).distance_to(&hit.point) < 1e-7, "{hit:?}");
+            assert!(b.point_at(hit.param_b).distance_to(&hit.point) < 1e-7, "{hit:?}");
+        }
+    }
+
+    #[test]
+    fn test_lines_and_circles() {
+        use IntersectionKind::*;
+
+        let cross = intersect(&segment(0.0, 0.0, 10.0, 10.0), &segment(0.0, 10.0, 10.0, 0.0));
+        assert_eq!(kinds(&cross), vec![Crossing]);
+        assert!(cross[0].point.approx_eq_eps(&Point2D::new(5.0, 5.0), TOL));
+        assert!(intersect(&segment(0.0, 0.0, 1.0, 0.0), &segment(0.0, 1.0, 1.0, 1.0)).is_empty());
+
+        let circle = Circle2D::new(Point2D::origin(), 2.0);
+        let hits = intersect(&segment(-5.0, 1.0, 5.0, 1.0), &circle);
+        assert_eq!(kinds(&hits), vec![Crossing, Crossing]);
+        assert!(hits[0].point.approx_eq_eps(&Point2D::new(-3.0_f64.sqrt(), 1.0), TOL));
+        assert_on_both(&segment(-5.0, 1.0, 5.0, 1.0), &circle, &hits);
+
+        // Touching, or dipping into the circle by less than the tolerance,
+        // is one tangent point rather than two near-duplicates
+        for y in [2.0, 2.0 - 1e-12, 2.0 - 5e-10] {
+            let hits = intersect(&segment(-5.0, y, 5.0, y), &circle);
+            assert_eq!(kinds(&hits), vec![Tangent], "y = {y}");
+            assert!(hits[0].point.approx_eq_eps(&Point2D::new(0.0, 2.0), 1e-6));
+        }
+
+        let touching = Circle2D::new(Point2D::new(3.0, 0.0), 1.0);
+        let hits = intersect(&circle, &touching);
+        assert_eq!(kinds(&hits), vec![Tangent]);
+        assert!(hits[0].point.approx_eq_eps(&Point2D::new(2.0, 0.0), TOL));
+        assert!((hits[0].param_b - PI).abs() < TOL);
+
+        // Arc parameters are the angle swept from the start
+        let upper = Arc2D::new(Point2D::origin(), 2.0, PI, 0.0, false);
+        let hits = intersect(&upper, &segment(0.0, -5.0, 0.0, 5.0));
+        assert_eq!(hits.len(), 1);
+        assert!((hits[0].param_a - PI / 2.0).abs() < TOL);
+        assert!(hits[0].point.approx_eq_eps(&Point2D::new(0.0, 2.0), TOL));
+    }
+
+    #[test]
+    fn test_overlaps() {
+        use IntersectionKind::*;
+
+        let hits = intersect(&segment(0.0, 0.0, 10.0, 0.0), &segment(12.0, 0.0, 4.0, 0.0));
+        assert_eq!(kinds(&hits), vec![Overlap, Overlap]);
+        assert!((hits[0].param_a - 0.4).abs() < TOL && (hits[0].param_b - 1.0).abs() < TOL);
+        assert!((hits[1].param_a - 1.0).abs() < TOL && (hits[1].param_b - 0.25).abs() < TOL);
+
+        // End to end on the same line only touches
+        let hits = intersect(&segment(0.0, 0.0, 1.0, 0.0), &segment(1.0, 0.0, 2.0, 0.0));
+        assert_eq!(kinds(&hits), vec![Tangent]);
+
+        let a = Arc2D::new(Point2D::origin(), 1.0, 0.0, PI, true);
+        let b = Arc2D::new(Point2D::origin(), 1.0, 3.0 * PI / 2.0, PI / 2.0, true);
+        let hits = intersect(&a, &b);
+        assert_eq!(kinds(&hits), vec![Overlap, Overlap]);
+        assert!(hits[0].point.approx_eq_eps(&Point2D::new(1.0, 0.0), TOL));
+        assert!(hits[1].point.approx_eq_eps(&Point2D::new(0.0, 1.0), TOL));
+        assert!(intersect(&Circle2D::new(Point2D::origin(), 1.0), &Circle2D::new(Point2D::origin(), 1.0)).is_empty());
+    }
+
+    #[test]
+    fn test_near_parallel_crossing() {
+        // Crossing at a slope difference of 2e-10: too shallow for the
+        // tolerance test, still decided exactly
+        let p = segment(0.0, 0.0, 10.0, 1e-9);
+        let q = segment(0.0, 1e-9, 10.0, 0.0);
+        assert!(segments_cross(&p, &q));
+        assert!(!segments_cross(&p, &segment(0.0, 1e-9, 10.0, 2e-9)));
+        assert!(!segments_cross(&p, &segment(10.0, 1e-9, 20.0, 0.0)));
+
+        let tolerance = Tolerance::new(1e-12, 1e-7);
+        assert!(intersect_within(&p, &q, &tolerance).is_empty());
+        let hits = intersect_within(&p, &q, &tolerance.with_predicates(PredicateMode::Exact));
+        assert_eq!(hits.len(), 1);
+        assert!((hits[0].param_a - 0.5).abs() < TOL);
+    }
+
+    #[test]
+    fn test_ellipses() {
+        use IntersectionKind::*;
+
+        let ellipse = Ellipse2D::new(Point2D::origin(), 2.0, 1.0, 0.0);
+        let line = segment(-5.0, 0.5, 5.0, 0.5);
+        let hits = intersect(&ellipse, &line);
+        assert_eq!(kinds(&hits), vec![Crossing, Crossing]);
+        assert!(hits[0].point.approx_eq_eps(&Point2D::new(3.0_f64.sqrt(), 0.5), TOL));
+        assert!(hits[1].point.approx_eq_eps(&Point2D::new(-3.0_f64.sqrt(), 0.5), TOL));
+        assert_on_both(&ellipse, &line, &hits);
+
+        // The unit circle touches the ellipse at the ends of its minor axis
+        let hits = intersect(&Circle2D::new(Point2D::origin(), 1.0), &ellipse);
+        assert_eq!(kinds(&hits), vec![Tangent, Tangent]);
+        assert!(hits[0].point.approx_eq_eps(&Point2D::new(0.0, 1.0), 1e-6));
+        assert!(hits[1].point.approx_eq_eps(&Point2D::new(0.0, -1.0), 1e-6));
+
+        let rotated = Ellipse2D::new(Point2D::origin(), 2.0, 1.0, PI / 2.0);
+        let hits = intersect(&ellipse, &rotated);
+        assert_eq!(kinds(&hits), vec![Crossing; 4]);
+        assert_on_both(&ellipse, &rotated, &hits);
+
+        let half = EllipticalArc2D::new(Point2D::origin(), 2.0, 1.0, 0.0, 0.0, PI, true);
+        assert_eq!(intersect(&half, &line).len(), 2);
+        assert!(intersect(&half, &segment(-5.0, -0.5, 5.0, -0.5)).is_empty());
+    }
+
+    #[test]
+    fn test_parabolas_and_hyperbolas() {
+        use IntersectionKind::*;
+
+        // y = x² / 4 from x = -3 to 4, its own parameter running backwards
+        let parabola = Parabola2D::new(Point2D::origin(), 1.0, PI / 2.0, 1.5, -2.0);
+        let line = segment(-5.0, 1.0, 5.0, 1.0);
+        let hits = intersect(&parabola, &line);
+        assert_eq!(kinds(&hits), vec![Crossing, Crossing]);
+        assert!(hits[0].point.approx_eq_eps(&Point2D::new(-2.0, 1.0), TOL));
+        assert!(hits[1].point.approx_eq_eps(&Point2D::new(2.0, 1.0), TOL));
+        assert!((hits[0].param_a - 0.5).abs() < TOL);
+        assert_on_both(&parabola, &line, &hits);
+        let hits = intersect(&parabola, &segment(-5.0, 0.0, 5.0, 0.0));
+        assert_eq!(kinds(&hits), vec![Tangent]);
+
+        // x² - y² = 1, right branch, against the unit circle and a parabola
+        let hyperbola = Hyperbola2D::new(Point2D::origin(), 1.0, 1.0, 0.0, -2.0, 2.0);
+        let hits = intersect(&hyperbola, &Circle2D::new(Point2D::origin(), 1.0));
+        assert_eq!(kinds(&hits), vec![Tangent]);
+        assert!(hits[0].point.approx_eq_eps(&Point2D::new(1.0, 0.0), 1e-6));
+        let sideways = Parabola2D::new(Point2D::new(3.0, 0.0), 0.25, PI, -4.0, 4.0);
+        let hits = intersect(&hyperbola, &sideways);
+        assert_eq!(hits.len(), 2);
+        assert_on_both(&hyperbola, &sideways, &hits);
+        assert!(hits.iter().all(|h| (h.point.x * h.point.x - h.point.y * h.point.y - 1.0).abs() < 1e-8));
+    }
+
+    #[test]
+    fn test_splines() {
+        use IntersectionKind::*;
+
+        // Quadratic hump peaking at (1, 1)
+        let hump = BSpline::clamped(vec![Point2D::new(0.0, 0.0), Point2D::new(1.0, 2.0), Point2D::new(2.0, 0.0)], 2)
+            .unwrap();
+        let line = segment(-1.0, 0.5, 3.0, 0.5);
+        let hits = intersect(&hump, &line);
+        assert_eq!(kinds(&hits), vec![Crossing, Crossing]);
+        assert_on_both(&hump, &line, &hits);
+        assert!((hits[0].point.x - (1.0 - 0.5_f64.sqrt())).abs() < TOL);
+
+        for y in [1.0, 1.0 - 1e-12] {
+            let hits = intersect(&hump, &segment(-1.0, y, 3.0, y));
+            assert_eq!(kinds(&hits), vec![Tangent], "y = {y}");
+            assert!(hits[0].point.approx_eq_eps(&Point2D::new(1.0, 1.0), 1e-5));
+        }
+        assert!(intersect(&hump, &segment(-1.0, 1.1, 3.0, 1.1)).is_empty());
+
+        let wave = BSpline::clamped(
+            (0..8).map(|i| Point2D::new(i as f64, if i % 2 == 0 { -1.0 } else { 1.0 })).collect(),
+            3,
+        )
+        .unwrap();
+        let circle = Circle2D::new(Point2D::new(3.5, 0.0), 1.5);
+        let hits = intersect(&wave, &circle);
+        assert!(!hits.is_empty() && hits.len() % 2 == 0);
+        assert_on_both(&wave, &circle, &hits);
+        assert!(hits.windows(2).all(|w| w[0].param_a < w[1].param_a));
+
+        let arch = NurbsCurve::clamped(
+            vec![Point2D::new(1.0, 0.0), Point2D::new(1.0, 1.0), Point2D::new(0.0, 1.0)],
+            vec![1.0, std::f64::consts::FRAC_1_SQRT_2, 1.0],
+            2,
+        )
+        .unwrap();
+        let diagonal = segment(0.0, 0.0, 2.0, 2.0);
+        let hits = intersect(&arch, &diagonal);
+        assert_eq!(kinds(&hits), vec![Crossing]);
+        let expected = std::f64::consts::FRAC_1_SQRT_2;
+        assert!(hits[0].point.approx_eq_eps(&Point2D::new(expected, expected), TOL));
+        assert_eq!(intersect(&hump, &wave).len(), intersect(&wave, &hump).len());
+    }
+}
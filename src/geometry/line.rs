@@ -5,6 +5,7 @@
 
 use crate::core::*;
 use crate::geometry::point::Point2D;
+use crate::geometry::simplify::{self, SmoothOptions};
 use nalgebra::Point2 as NPoint2;
 use serde::{Deserialize, Serialize};
 
@@ -556,12 +557,13 @@ impl Polyline2D {
 
     /// Simplify the polyline using Douglas-Peucker algorithm
     pub fn simplify(&self, tolerance: f64) -> Polyline2D {
-        if self.vertices.len() < 3 {
-            return self.clone();
-        }
+        Polyline2D::new(simplify::simplify(&self.vertices, tolerance, self.closed), self.closed)
+    }
 
-        let simplified = douglas_peucker(&self.vertices, tolerance);
-        Polyline2D::new(simplified, self.closed)
+    /// Smooth the polyline, moving no vertex further than `tolerance`
+    pub fn smooth(&self, tolerance: f64, options: &SmoothOptions) -> Polyline2D {
+        let vertices = simplify::smooth(&self.vertices, tolerance, self.closed, options);
+        Polyline2D::new(vertices, self.closed)
     }
 
     /// Check if the polyline is self-intersecting
@@ -582,36 +584,6 @@ impl Polyline2D {
     }
 }
 
-/// Douglas-Peucker line simplification algorithm
-fn douglas_peucker(points: &[Point2D], tolerance: f64) -> Vec<Point2D> {
-    if points.len() < 3 {
-        return points.to_vec();
-    }
-
-    let first = points.first().unwrap();
-    let last = points.last().unwrap();
-    let line = LineSegment2D::new(*first, *last);
-
-    let (max_distance, max_index) = points
-        .iter()
-        .enumerate()
-        .skip(1)
-        .take(points.len() - 2)
-        .map(|(i, p)| (line.distance_to_point(p), i))
-        .max_by(|(d1, _), (d2, _)| d1.partial_cmp(d2).unwrap())
-        .unwrap_or((0.0, 0));
-
-    if max_distance > tolerance {
-        let mut left = douglas_peucker(&points[0..=max_index], tolerance);
-        let right = douglas_peucker(&points[max_index..], tolerance);
-        left.pop(); // Remove duplicate point
-        left.extend(right);
-        left
-    } else {
-        vec![*first, *last]
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
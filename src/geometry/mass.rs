@@ -0,0 +1,367 @@
+//! Mass properties of solids and planar regions
+//!
+//! [`SolidProperties`] integrates over the volume a closed surface bounds:
+//! volume, surface area, centroid, and the inertia tensor about the
+//! centroid, following Eberly's "Polyhedral Mass Properties (Revisited)".
+//! Each face contributes the integrals of 1, x, y, z and their products
+//! over the tetrahedra it spans with the origin, so the surface only has to
+//! be closed and consistently wound; concave and multi-shell solids need no
+//! special handling. Inward winding is detected from the sign of the volume
+//! and corrected.
+//!
+//! [`AreaProperties`] does the same for a planar region bounded by an outer
+//! loop and holes, with Green's theorem over the polygon edges: area,
+//! perimeter, centroid, and second moments of area about the centroid.
+//! Curved boundaries are flattened first, so their results carry the
+//! flattening tolerance.
+//!
+//! Inertia uses a density: mass per unit volume for solids, and mass per
+//! unit area (density times thickness, for plates) for regions. Second
+//! moments of area are purely geometric and take no density.
+
+use crate::core::precision::EPSILON;
+use crate::geometry::point::Point2D;
+use crate::geometry::polygon::Polygon2D;
+use nalgebra::{Matrix3, Point3, Vector3};
+use serde::{Deserialize, Serialize};
+
+/// Volume properties of a closed solid
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolidProperties {
+    /// Enclosed volume
+    pub volume: f64,
+    /// Area of the bounding surface
+    pub surface_area: f64,
+    /// Center of the volume, which is the center of mass at uniform density
+    pub centroid: Point3<f64>,
+    /// Mass per unit volume
+    pub density: f64,
+    /// Volume times density
+    pub mass: f64,
+    /// Inertia tensor about the centroid, products of inertia negated
+    pub inertia: Matrix3<f64>,
+    /// Principal moments of inertia, smallest first
+    pub principal_moments: [f64; 3],
+    /// Principal axes as unit columns, in the order of the moments
+    pub principal_axes: Matrix3<f64>,
+    /// Axis-aligned bounds, minimum and maximum corner
+    pub bounds: (Point3<f64>, Point3<f64>),
+}
+
+impl SolidProperties {
+    /// Properties of the solid bounded by planar faces
+    ///
+    /// Each face is a loop of at least three points. Returns `None` when
+    /// the faces enclose no volume.
+    pub fn from_faces(faces: &[Vec<Point3<f64>>], density: f64) -> Option<Self> {
+        // Integrals of 1, x, y, z, x², y², z², xy, yz, zx over the volume
+        let mut integrals = [0.0; 10];
+        let mut surface_area = 0.0;
+        let mut bounds: Option<(Point3<f64>, Point3<f64>)> = None;
+        for face in faces.iter().filter(|f| f.len() >= 3) {
+            let mut normal = Vector3::zeros();
+            for (i, p) in face.iter().enumerate() {
+                normal += p.coords.cross(&face[(i + 1) % face.len()].coords);
+                bounds = Some(match bounds {
+                    Some((lo, hi)) => (lo.inf(p), hi.sup(p)),
+                    None => (*p, *p),
+                });
+            }
+            surface_area += normal.norm() / 2.0;
+            for i in 1..face.len() - 1 {
+                add_triangle(&mut integrals, &face[0], &face[i], &face[i + 1]);
+            }
+        }
+
+        let factors = [
+            1.0 / 6.0,
+            1.0 / 24.0,
+            1.0 / 24.0,
+            1.0 / 24.0,
+            1.0 / 60.0,
+            1.0 / 60.0,
+            1.0 / 60.0,
+            1.0 / 120.0,
+            1.0 / 120.0,
+            1.0 / 120.0,
+        ];
+        for (value, factor) in integrals.iter_mut().zip(factors) {
+            *value *= factor;
+        }
+        // Inward winding negates every integral
+        if integrals[0] < 0.0 {
+            integrals.iter_mut().for_each(|v| *v = -*v);
+        }
+
+        let volume = integrals[0];
+        if volume < EPSILON {
+            return None;
+        }
+        let c = Vector3::new(integrals[1], integrals[2], integrals[3]) / volume;
+        let xx = integrals[4] - volume * c.x * c.x;
+        let yy = integrals[5] - volume * c.y * c.y;
+        let zz = integrals[6] - volume * c.z * c.z;
+        let xy = integrals[7] - volume * c.x * c.y;
+        let yz = integrals[8] - volume * c.y * c.z;
+        let zx = integrals[9] - volume * c.z * c.x;
+        #[rustfmt::skip]
+        let inertia = Matrix3::new(
+            yy + zz, -xy, -zx,
+            -xy, zz + xx, -yz,
+            -zx, -yz, xx + yy,
+        ) * density;
+
+        let (principal_moments, principal_axes) = principal(&inertia);
+        Some(Self {
+            volume,
+            surface_area,
+            centroid: Point3::from(c),
+            density,
+            mass: volume * density,
+            inertia,
+            principal_moments,
+            principal_axes,
+            bounds: bounds?,
+        })
+    }
+
+    /// Radii of gyration about the principal axes, in their order
+    pub fn radii_of_gyration(&self) -> [f64; 3] {
+        self.principal_moments
+            .map(|moment| if self.mass > 0.0 { (moment / self.mass).max(0.0).sqrt() } else { 0.0 })
+    }
+
+    /// Moment of inertia about an axis through a point, by the parallel
+    /// axis theorem
+    pub fn moment_about(&self, point: &Point3<f64>, axis: &Vector3<f64>) -> f64 {
+        let Some(axis) = axis.try_normalize(EPSILON) else {
+            return 0.0;
+        };
+        let offset = self.centroid - point;
+        let distance_sq = offset.norm_squared() - offset.dot(&axis).powi(2);
+        axis.dot(&(self.inertia * axis)) + self.mass * distance_sq.max(0.0)
+    }
+}
+
+/// Add one triangle's contribution to the volume integrals
+fn add_triangle(integrals: &mut [f64; 10], p0: &Point3<f64>, p1: &Point3<f64>, p2: &Point3<f64>) {
+    let (e1, e2) = (p1 - p0, p2 - p0);
+    let d = e1.cross(&e2);
+    let (f1x, f2x, f3x, g0x, g1x, g2x) = subexpressions(p0.x, p1.x, p2.x);
+    let (_, f2y, f3y, g0y, g1y, g2y) = subexpressions(p0.y, p1.y, p2.y);
+    let (_, f2z, f3z, g0z, g1z, g2z) = subexpressions(p0.z, p1.z, p2.z);
+
+    integrals[0] += d.x * f1x;
+    integrals[1] += d.x * f2x;
+    integrals[2] += d.y * f2y;
+    integrals[3] += d.z * f2z;
+    integrals[4] += d.x * f3x;
+    integrals[5] += d.y * f3y;
+    integrals[6] += d.z * f3z;
+    integrals[7] += d.x * (p0.y * g0x + p1.y * g1x + p2.y * g2x);
+    integrals[8] += d.y * (p0.z * g0y + p1.z * g1y + p2.z * g2y);
+    integrals[9] += d.z * (p0.x * g0z + p1.x * g1z + p2.x * g2z);
+}
+
+/// Polynomial sums of one coordinate of a triangle's corners
+fn subexpressions(w0: f64, w1: f64, w2: f64) -> (f64, f64, f64, f64, f64, f64) {
+    let temp0 = w0 + w1;
+    let f1 = temp0 + w2;
+    let temp1 = w0 * w0;
+    let temp2 = temp1 + w1 * temp0;
+    let f2 = temp2 + w2 * f1;
+    let f3 = w0 * temp1 + w1 * temp2 + w2 * f2;
+    let g0 = f2 + w0 * (f1 + w0);
+    let g1 = f2 + w1 * (f1 + w1);
+    let g2 = f2 + w2 * (f1 + w2);
+    (f1, f2, f3, g0, g1, g2)
+}
+
+/// Eigenvalues of a symmetric tensor, smallest first, with their unit
+/// eigenvectors as columns
+fn principal(tensor: &Matrix3<f64>) -> ([f64; 3], Matrix3<f64>) {
+    let eigen = tensor.symmetric_eigen();
+    let mut order = [0, 1, 2];
+    order.sort_by(|&a, &b| eigen.eigenvalues[a].total_cmp(&eigen.eigenvalues[b]));
+    let moments = order.map(|i| eigen.eigenvalues[i]);
+    let axes = Matrix3::from_columns(&order.map(|i| eigen.eigenvectors.column(i).into_owned()));
+    (moments, axes)
+}
+
+/// Area properties of a planar region
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AreaProperties {
+    /// Area inside the outer loop and outside the holes
+    pub area: f64,
+    /// Length of all the loops
+    pub perimeter: f64,
+    /// Center of the area
+    pub centroid: Point2D,
+    /// Second moment of area about the horizontal axis through the
+    /// centroid, the integral of y²
+    pub ixx: f64,
+    /// Second moment of area about the vertical axis through the centroid,
+    /// the integral of x²
+    pub iyy: f64,
+    /// Product of area about the centroid, the integral of xy
+    pub ixy: f64,
+}
+
+impl AreaProperties {
+    /// Properties of a polygon less its holes; `None` for a polygon
+    /// without area
+    ///
+    /// Loops may wind either way: the outer loop counts positive and holes
+    /// negative whatever their direction.
+    pub fn of_polygon(polygon: &Polygon2D) -> Option<Self> {
+        // Area, first moments, and second moments about the origin
+        let mut totals = [0.0; 6];
+        let mut perimeter = 0.0;
+        for (index, ring) in std::iter::once(&polygon.vertices).chain(&polygon.holes).enumerate() {
+            if ring.len() < 3 {
+                continue;
+            }
+            let mut sums = [0.0; 6];
+            for (i, a) in ring.iter().enumerate() {
+                let b = ring[(i + 1) % ring.len()];
+                perimeter += a.distance_to(&b);
+                let cross = a.x * b.y - b.x * a.y;
+                sums[0] += cross;
+                sums[1] += (a.x + b.x) * cross;
+                sums[2] += (a.y + b.y) * cross;
+                sums[3] += (a.y * a.y + a.y * b.y + b.y * b.y) * cross;
+                sums[4] += (a.x * a.x + a.x * b.x + b.x * b.x) * cross;
+                sums[5] += (a.x * b.y + 2.0 * a.x * a.y + 2.0 * b.x * b.y + b.x * a.y) * cross;
+            }
+            let sign = if (sums[0] >= 0.0) == (index == 0) { 1.0 } else { -1.0 };
+            let divisors = [2.0, 6.0, 6.0, 12.0, 12.0, 24.0];
+            for ((total, sum), divisor) in totals.iter_mut().zip(sums).zip(divisors) {
+                *total += sign * sum / divisor;
+            }
+        }
+
+        let area = totals[0];
+        if area < EPSILON {
+            return None;
+        }
+        let (cx, cy) = (totals[1] / area, totals[2] / area);
+        Some(Self {
+            area,
+            perimeter,
+            centroid: Point2D::new(cx, cy),
+            ixx: totals[3] - area * cy * cy,
+            iyy: totals[4] - area * cx * cx,
+            ixy: totals[5] - area * cx * cy,
+        })
+    }
+
+    /// Polar moment of area about the centroid
+    pub fn polar_moment(&self) -> f64 {
+        self.ixx + self.iyy
+    }
+
+    /// Principal second moments of area, largest first, and the angle in
+    /// radians from the x axis to the axis of the largest
+    pub fn principal_moments(&self) -> (f64, f64, f64) {
+        let mean = (self.ixx + self.iyy) / 2.0;
+        let radius = (((self.ixx - self.iyy) / 2.0).powi(2) + self.ixy * self.ixy).sqrt();
+        let angle = 0.5 * (-2.0 * self.ixy).atan2(self.ixx - self.iyy);
+        (mean + radius, mean - radius, angle)
+    }
+
+    /// Radii of gyration about the horizontal and vertical centroidal axes
+    pub fn radii_of_gyration(&self) -> (f64, f64) {
+        ((self.ixx / self.area).sqrt(), (self.iyy / self.area).sqrt())
+    }
+
+    /// Mass of a plate of the region at a mass per unit area
+    pub fn mass(&self, areal_density: f64) -> f64 {
+        self.area * areal_density
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Faces of the box `[0, a] x [0, b] x [0, c]`, wound outward
+    fn block(a: f64, b: f64, c: f64) -> Vec<Vec<Point3<f64>>> {
+        let p = |x: f64, y: f64, z: f64| Point3::new(x * a, y * b, z * c);
+        vec![
+            vec![p(0.0, 0.0, 0.0), p(0.0, 1.0, 0.0), p(1.0, 1.0, 0.0), p(1.0, 0.0, 0.0)],
+            vec![p(0.0, 0.0, 1.0), p(1.0, 0.0, 1.0), p(1.0, 1.0, 1.0), p(0.0, 1.0, 1.0)],
+            vec![p(0.0, 0.0, 0.0), p(1.0, 0.0, 0.0), p(1.0, 0.0, 1.0), p(0.0, 0.0, 1.0)],
+            vec![p(0.0, 1.0, 0.0), p(0.0, 1.0, 1.0), p(1.0, 1.0, 1.0), p(1.0, 1.0, 0.0)],
+            vec![p(0.0, 0.0, 0.0), p(0.0, 0.0, 1.0), p(0.0, 1.0, 1.0), p(0.0, 1.0, 0.0)],
+            vec![p(1.0, 0.0, 0.0), p(1.0, 1.0, 0.0), p(1.0, 1.0, 1.0), p(1.0, 0.0, 1.0)],
+        ]
+    }
+
+    #[test]
+    fn test_block_inertia() {
+        let props = SolidProperties::from_faces(&block(2.0, 3.0, 4.0), 2.0).unwrap();
+        assert!((props.volume - 24.0).abs() < 1e-9);
+        assert!((props.surface_area - 52.0).abs() < 1e-9);
+        assert!((props.mass - 48.0).abs() < 1e-9);
+        assert!((props.centroid - Point3::new(1.0, 1.5, 2.0)).norm() < 1e-9);
+
+        // m (b² + c²) / 12 about each axis, no products of inertia
+        let expected = Matrix3::from_diagonal(&Vector3::new(100.0, 80.0, 52.0));
+        assert!((props.inertia - expected).norm() < 1e-9);
+        assert_eq!(props.principal_moments.map(|m| m.round()), [52.0, 80.0, 100.0]);
+        assert!((props.principal_axes.column(0).z.abs() - 1.0).abs() < 1e-9);
+        assert!((props.radii_of_gyration()[2] - (100.0f64 / 48.0).sqrt()).abs() < 1e-9);
+
+        // About an edge of the block, 48 * (2² + 3²) / 3 by parallel axes
+        let edge = props.moment_about(&Point3::origin(), &Vector3::z());
+        assert!((edge - 208.0).abs() < 1e-9);
+
+        // Inward winding gives the same result
+        let inward: Vec<_> = block(2.0, 3.0, 4.0)
+            .into_iter()
+            .map(|f| f.into_iter().rev().collect())
+            .collect();
+        let flipped = SolidProperties::from_faces(&inward, 2.0).unwrap();
+        assert!((flipped.inertia - props.inertia).norm() < 1e-9);
+        assert!(SolidProperties::from_faces(&[], 1.0).is_none());
+    }
+
+    #[test]
+    fn test_region_moments() {
+        let rect = |x0: f64, y0: f64, x1: f64, y1: f64| {
+            vec![
+                Point2D::new(x0, y0),
+                Point2D::new(x1, y0),
+                Point2D::new(x1, y1),
+                Point2D::new(x0, y1),
+            ]
+        };
+        let plate = AreaProperties::of_polygon(&Polygon2D::new(rect(0.0, 0.0, 4.0, 2.0))).unwrap();
+        assert!((plate.area - 8.0).abs() < 1e-9);
+        assert!((plate.perimeter - 12.0).abs() < 1e-9);
+        assert_eq!(plate.centroid, Point2D::new(2.0, 1.0));
+        assert!((plate.ixx - 4.0 * 8.0 / 12.0).abs() < 1e-9);
+        assert!((plate.iyy - 2.0 * 64.0 / 12.0).abs() < 1e-9);
+        assert!(plate.ixy.abs() < 1e-9);
+        let (major, minor, angle) = plate.principal_moments();
+        assert!((major - plate.iyy).abs() < 1e-9 && (minor - plate.ixx).abs() < 1e-9);
+        assert!((angle.abs() - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+
+        // A hole wound the same way as the outline still counts negative
+        let holed = Polygon2D::with_holes(rect(0.0, 0.0, 4.0, 2.0), vec![rect(3.0, 0.5, 4.0, 1.5)]);
+        let holed = AreaProperties::of_polygon(&holed).unwrap();
+        assert!((holed.area - 7.0).abs() < 1e-9);
+        assert!((holed.centroid.x - (8.0 * 2.0 - 3.5) / 7.0).abs() < 1e-9);
+        assert!((holed.mass(0.5) - 3.5).abs() < 1e-9);
+
+        // Square tilted 30 degrees has equal principal moments at any angle
+        let (s, c) = 30f64.to_radians().sin_cos();
+        let tilted: Vec<Point2D> = rect(-1.0, -1.0, 1.0, 1.0)
+            .into_iter()
+            .map(|p| Point2D::new(p.x * c - p.y * s, p.x * s + p.y * c))
+            .collect();
+        let tilted = AreaProperties::of_polygon(&Polygon2D::new(tilted)).unwrap();
+        assert!((tilted.ixx - 16.0 / 12.0).abs() < 1e-9 && tilted.ixy.abs() < 1e-9);
+        assert!(AreaProperties::of_polygon(&Polygon2D::new(rect(0.0, 0.0, 0.0, 1.0))).is_none());
+    }
+}
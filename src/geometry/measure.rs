@@ -0,0 +1,646 @@
+//! Arc-length parameterization
+//!
+//! [`ArcLength`] measures distance along any 2D curve: its length, the point
+//! a given distance from the start, and evenly spaced points for dividing a
+//! curve into equal parts or stepping along it at a fixed interval (array
+//! along path, dimensions and markers along curves).
+//!
+//! Segments, polylines, arcs and circles are measured exactly. Ellipses,
+//! hyperbolas, Bezier curves and splines have no closed form, so their speed
+//! is integrated with Gauss-Legendre quadrature over short pieces and the
+//! distance is inverted with safeguarded Newton iteration; results are good
+//! to around 1e-10 of the curve's length. Parabolas are measured the same
+//! way, as their closed form cannot be inverted either.
+
+use crate::geometry::arc::{Arc2D, Circle2D, Ellipse2D, EllipticalArc2D};
+use crate::geometry::conic::{Hyperbola2D, Parabola2D};
+use crate::geometry::curve::{BSpline, BezierCurve, NurbsCurve};
+use crate::geometry::fitting::{ArcPolyline, FitSegment};
+use crate::geometry::intersect::CurveRef;
+use crate::geometry::line::{LineSegment2D, Polyline2D};
+use crate::geometry::point::Point2D;
+
+/// Quadrature pieces per smooth span (quarter turn or knot span)
+const PIECES_PER_SPAN: usize = 8;
+
+/// Quadrature pieces across a Bezier curve
+const BEZIER_PIECES: usize = 16;
+
+/// Newton iterations when inverting distance to parameter
+const MAX_ITERATIONS: usize = 50;
+
+/// Five-point Gauss-Legendre nodes and weights on [-1, 1]
+const GAUSS: [(f64, f64); 5] = [
+    (0.0, 0.568_888_888_888_888_9),
+    (-0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+    (0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+    (-0.906_179_845_938_664, 0.236_926_885_056_189_1),
+    (0.906_179_845_938_664, 0.236_926_885_056_189_1),
+];
+
+/// Distance along a curve
+///
+/// Distances are measured from the curve's start point. Open curves clamp
+/// distances to their ends; closed curves wrap them around.
+pub trait ArcLength {
+    /// Length of the curve
+    fn length(&self) -> f64;
+
+    /// Point `distance` along the curve from its start
+    fn point_at_length(&self, distance: f64) -> Point2D;
+
+    /// Whether the curve ends where it starts
+    fn is_closed(&self) -> bool {
+        false
+    }
+
+    /// Points at each of `distances`
+    ///
+    /// Curves measured numerically override this to integrate once for all
+    /// the distances.
+    fn points_at_lengths(&self, distances: &[f64]) -> Vec<Point2D> {
+        distances.iter().map(|&d| self.point_at_length(d)).collect()
+    }
+
+    /// Points splitting the curve into `parts` pieces of equal length
+    ///
+    /// Both ends are included on open curves, so there are `parts + 1`
+    /// points; closed curves give `parts` points starting at the start.
+    fn divide(&self, parts: usize) -> Vec<Point2D> {
+        if parts == 0 {
+            return Vec::new();
+        }
+        let length = self.length();
+        let count = if self.is_closed() { parts } else { parts + 1 };
+        let distances: Vec<f64> = (0..count)
+            .map(|i| length * i as f64 / parts as f64)
+            .collect();
+        self.points_at_lengths(&distances)
+    }
+
+    /// Points every `step` along the curve, starting at the start
+    ///
+    /// The last point is at or before the end; on closed curves a point
+    /// landing back on the start is left out. A step that is not positive
+    /// gives the start point alone.
+    fn measure(&self, step: f64) -> Vec<Point2D> {
+        let length = self.length();
+        if !(step > 0.0 && step.is_finite()) {
+            return self.points_at_lengths(&[0.0]);
+        }
+        // Allow for rounding when the step divides the length exactly
+        let slack = length * 1e-12;
+        let mut count = ((length + slack) / step).floor() as usize;
+        if self.is_closed() && count > 0 && (count as f64 * step - length).abs() <= slack {
+            count -= 1;
+        }
+        let distances: Vec<f64> = (0..=count).map(|i| (i as f64 * step).min(length)).collect();
+        self.points_at_lengths(&distances)
+    }
+}
+
+/// Clamp `distance` to an open curve's ends or wrap it around a closed one
+fn normalize(distance: f64, length: f64, closed: bool) -> f64 {
+    if closed && length > 0.0 {
+        distance.rem_euclid(length)
+    } else {
+        distance.clamp(0.0, length.max(0.0))
+    }
+}
+
+/// Cumulative length table for a curve measured by quadrature
+struct Table<P, V> {
+    position: P,
+    velocity: V,
+    params: Vec<f64>,
+    lengths: Vec<f64>,
+}
+
+impl<P, V> Table<P, V>
+where
+    P: Fn(f64) -> Point2D,
+    V: Fn(f64) -> Point2D,
+{
+    /// Integrate over pieces with the given parameter breaks
+    fn new(position: P, velocity: V, params: Vec<f64>) -> Self {
+        let mut table = Self {
+            position,
+            velocity,
+            params,
+            lengths: Vec::new(),
+        };
+        let mut total = 0.0;
+        table.lengths.push(0.0);
+        for piece in table.params.windows(2) {
+            total += table.integrate(piece[0], piece[1]);
+            table.lengths.push(total);
+        }
+        table
+    }
+
+    fn speed(&self, t: f64) -> f64 {
+        (self.velocity)(t).distance_to_origin()
+    }
+
+    fn integrate(&self, a: f64, b: f64) -> f64 {
+        let (half, mid) = ((b - a) / 2.0, (a + b) / 2.0);
+        GAUSS
+            .iter()
+            .map(|&(x, w)| w * self.speed(mid + half * x))
+            .sum::<f64>()
+            * half
+    }
+
+    fn length(&self) -> f64 {
+        self.lengths.last().copied().unwrap_or(0.0)
+    }
+
+    /// Parameter `distance` along the curve, for a distance within its length
+    fn parameter_at(&self, distance: f64) -> f64 {
+        let Some(&first) = self.params.first() else {
+            return 0.0;
+        };
+        if self.params.len() < 2 {
+            return first;
+        }
+        let piece = self
+            .lengths
+            .partition_point(|&l| l <= distance)
+            .clamp(1, self.params.len() - 1)
+            - 1;
+        let (a, b) = (self.params[piece], self.params[piece + 1]);
+        let target = distance - self.lengths[piece];
+        let piece_length = self.lengths[piece + 1] - self.lengths[piece];
+        if piece_length <= 0.0 {
+            return a;
+        }
+
+        // Newton on s(t) = target, falling back to bisection when a step
+        // leaves the bracket
+        let tolerance = self.length() * 1e-13;
+        let (mut lo, mut hi) = (a, b);
+        let mut t = a + (b - a) * (target / piece_length).clamp(0.0, 1.0);
+        for _ in 0..MAX_ITERATIONS {
+            let error = self.integrate(a, t) - target;
+            if error.abs() <= tolerance {
+                break;
+            }
+            if error > 0.0 {
+                hi = t;
+            } else {
+                lo = t;
+            }
+            let speed = self.speed(t);
+            let next = t - error / speed;
+            t = if speed > 0.0 && next > lo && next < hi {
+                next
+            } else {
+                (lo + hi) / 2.0
+            };
+        }
+        t
+    }
+
+    fn points_at(&self, distances: &[f64], closed: bool) -> Vec<Point2D> {
+        let length = self.length();
+        distances
+            .iter()
+            .map(|&d| (self.position)(self.parameter_at(normalize(d, length, closed))))
+            .collect()
+    }
+}
+
+/// Split each span between `breaks` into `pieces` equal parts
+fn subdivide(breaks: &[f64], pieces: usize) -> Vec<f64> {
+    let mut params: Vec<f64> = breaks
+        .windows(2)
+        .flat_map(|span| {
+            (0..pieces).map(move |i| span[0] + (span[1] - span[0]) * i as f64 / pieces as f64)
+        })
+        .collect();
+    if let Some(&last) = breaks.last() {
+        params.push(last);
+    }
+    params
+}
+
+/// Table for conics and splines, through their [`CurveRef`] parameterization
+fn curve_table<'a>(
+    curve: CurveRef<'a>,
+) -> Table<impl Fn(f64) -> Point2D + 'a, impl Fn(f64) -> Point2D + 'a> {
+    let params = subdivide(&curve.breaks(), PIECES_PER_SPAN);
+    Table::new(
+        move |t| curve.point_at(t),
+        move |t| curve.derivative_at(t),
+        params,
+    )
+}
+
+/// Length of a chain of pieces
+fn chain_length<T: ArcLength>(pieces: &[T]) -> f64 {
+    pieces.iter().map(ArcLength::length).sum()
+}
+
+/// Point `distance` along a chain of pieces, clamped to its ends
+fn chain_point<T: ArcLength>(pieces: &[T], distance: f64) -> Option<Point2D> {
+    let mut remaining = distance;
+    for (i, piece) in pieces.iter().enumerate() {
+        let length = piece.length();
+        if remaining <= length || i == pieces.len() - 1 {
+            return Some(piece.point_at_length(remaining));
+        }
+        remaining -= length;
+    }
+    None
+}
+
+impl ArcLength for LineSegment2D {
+    fn length(&self) -> f64 {
+        LineSegment2D::length(self)
+    }
+
+    fn point_at_length(&self, distance: f64) -> Point2D {
+        let length = LineSegment2D::length(self);
+        if length <= 0.0 {
+            return self.start;
+        }
+        self.point_at(normalize(distance, length, false) / length)
+    }
+}
+
+impl ArcLength for Polyline2D {
+    fn length(&self) -> f64 {
+        Polyline2D::length(self)
+    }
+
+    fn point_at_length(&self, distance: f64) -> Point2D {
+        let segments = self.segments();
+        let distance = normalize(distance, chain_length(&segments), self.is_closed());
+        chain_point(&segments, distance)
+            .or_else(|| self.vertices.first().copied())
+            .unwrap_or_default()
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
+impl ArcLength for Arc2D {
+    fn length(&self) -> f64 {
+        Arc2D::length(self)
+    }
+
+    fn point_at_length(&self, distance: f64) -> Point2D {
+        if self.radius <= 0.0 {
+            return self.start_point();
+        }
+        let turned = normalize(distance, Arc2D::length(self), false) / self.radius;
+        let angle = if self.ccw {
+            self.start_angle + turned
+        } else {
+            self.start_angle - turned
+        };
+        self.point_at_angle(angle)
+    }
+}
+
+impl ArcLength for Circle2D {
+    fn length(&self) -> f64 {
+        self.circumference()
+    }
+
+    /// Measured counterclockwise from the point at angle zero
+    fn point_at_length(&self, distance: f64) -> Point2D {
+        if self.radius <= 0.0 {
+            return self.center;
+        }
+        self.point_at_angle(normalize(distance, self.circumference(), true) / self.radius)
+    }
+
+    fn is_closed(&self) -> bool {
+        true
+    }
+}
+
+impl ArcLength for Ellipse2D {
+    /// Exact length, unlike [`Ellipse2D::circumference`]'s approximation
+    fn length(&self) -> f64 {
+        curve_table(CurveRef::Ellipse(self)).length()
+    }
+
+    /// Measured from the end of the major axis, counterclockwise in the
+    /// ellipse's frame
+    fn point_at_length(&self, distance: f64) -> Point2D {
+        self.points_at_lengths(&[distance])[0]
+    }
+
+    fn is_closed(&self) -> bool {
+        true
+    }
+
+    fn points_at_lengths(&self, distances: &[f64]) -> Vec<Point2D> {
+        curve_table(CurveRef::Ellipse(self)).points_at(distances, true)
+    }
+}
+
+impl ArcLength for EllipticalArc2D {
+    fn length(&self) -> f64 {
+        curve_table(CurveRef::EllipticalArc(self)).length()
+    }
+
+    fn point_at_length(&self, distance: f64) -> Point2D {
+        self.points_at_lengths(&[distance])[0]
+    }
+
+    fn points_at_lengths(&self, distances: &[f64]) -> Vec<Point2D> {
+        curve_table(CurveRef::EllipticalArc(self)).points_at(distances, false)
+    }
+}
+
+impl ArcLength for Parabola2D {
+    fn length(&self) -> f64 {
+        curve_table(CurveRef::Parabola(self)).length()
+    }
+
+    fn point_at_length(&self, distance: f64) -> Point2D {
+        self.points_at_lengths(&[distance])[0]
+    }
+
+    fn points_at_lengths(&self, distances: &[f64]) -> Vec<Point2D> {
+        curve_table(CurveRef::Parabola(self)).points_at(distances, false)
+    }
+}
+
+impl ArcLength for Hyperbola2D {
+    fn length(&self) -> f64 {
+        curve_table(CurveRef::Hyperbola(self)).length()
+    }
+
+    fn point_at_length(&self, distance: f64) -> Point2D {
+        self.points_at_lengths(&[distance])[0]
+    }
+
+    fn points_at_lengths(&self, distances: &[f64]) -> Vec<Point2D> {
+        curve_table(CurveRef::Hyperbola(self)).points_at(distances, false)
+    }
+}
+
+impl ArcLength for BSpline {
+    fn length(&self) -> f64 {
+        curve_table(CurveRef::BSpline(self)).length()
+    }
+
+    fn point_at_length(&self, distance: f64) -> Point2D {
+        self.points_at_lengths(&[distance])[0]
+    }
+
+    fn points_at_lengths(&self, distances: &[f64]) -> Vec<Point2D> {
+        curve_table(CurveRef::BSpline(self)).points_at(distances, false)
+    }
+}
+
+impl ArcLength for NurbsCurve {
+    fn length(&self) -> f64 {
+        curve_table(CurveRef::Nurbs(self)).length()
+    }
+
+    fn point_at_length(&self, distance: f64) -> Point2D {
+        self.points_at_lengths(&[distance])[0]
+    }
+
+    fn points_at_lengths(&self, distances: &[f64]) -> Vec<Point2D> {
+        curve_table(CurveRef::Nurbs(self)).points_at(distances, false)
+    }
+}
+
+impl BezierCurve {
+    fn length_table(&self) -> Table<impl Fn(f64) -> Point2D + '_, impl Fn(f64) -> Point2D + '_> {
+        let derivative = self.derivative();
+        Table::new(
+            move |t| self.evaluate(t),
+            move |t| {
+                derivative
+                    .as_ref()
+                    .map_or_else(Point2D::origin, |d| d.evaluate(t))
+            },
+            subdivide(&[0.0, 1.0], BEZIER_PIECES),
+        )
+    }
+}
+
+impl ArcLength for BezierCurve {
+    fn length(&self) -> f64 {
+        self.length_table().length()
+    }
+
+    fn point_at_length(&self, distance: f64) -> Point2D {
+        self.points_at_lengths(&[distance])[0]
+    }
+
+    fn points_at_lengths(&self, distances: &[f64]) -> Vec<Point2D> {
+        self.length_table().points_at(distances, false)
+    }
+}
+
+impl ArcLength for FitSegment {
+    fn length(&self) -> f64 {
+        FitSegment::length(self)
+    }
+
+    fn point_at_length(&self, distance: f64) -> Point2D {
+        match self {
+            FitSegment::Line(line) => line.point_at_length(distance),
+            FitSegment::Arc(arc) => arc.point_at_length(distance),
+        }
+    }
+}
+
+impl ArcLength for ArcPolyline {
+    fn length(&self) -> f64 {
+        ArcPolyline::length(self)
+    }
+
+    fn point_at_length(&self, distance: f64) -> Point2D {
+        let segments = self.segments();
+        let distance = normalize(distance, chain_length(&segments), self.closed);
+        chain_point(&segments, distance)
+            .or_else(|| self.vertices.first().map(|v| v.point))
+            .unwrap_or_default()
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
+impl ArcLength for CurveRef<'_> {
+    fn length(&self) -> f64 {
+        match self {
+            CurveRef::Segment(line) => ArcLength::length(*line),
+            CurveRef::Arc(arc) => ArcLength::length(*arc),
+            CurveRef::Circle(circle) => circle.length(),
+            CurveRef::Ellipse(ellipse) => ellipse.length(),
+            CurveRef::EllipticalArc(arc) => arc.length(),
+            CurveRef::Parabola(parabola) => parabola.length(),
+            CurveRef::Hyperbola(hyperbola) => hyperbola.length(),
+            CurveRef::BSpline(spline) => spline.length(),
+            CurveRef::Nurbs(nurbs) => nurbs.length(),
+        }
+    }
+
+    fn point_at_length(&self, distance: f64) -> Point2D {
+        self.points_at_lengths(&[distance])[0]
+    }
+
+    fn is_closed(&self) -> bool {
+        matches!(self, CurveRef::Circle(_) | CurveRef::Ellipse(_))
+    }
+
+    fn points_at_lengths(&self, distances: &[f64]) -> Vec<Point2D> {
+        match self {
+            CurveRef::Segment(line) => line.points_at_lengths(distances),
+            CurveRef::Arc(arc) => arc.points_at_lengths(distances),
+            CurveRef::Circle(circle) => circle.points_at_lengths(distances),
+            CurveRef::Ellipse(ellipse) => ellipse.points_at_lengths(distances),
+            CurveRef::EllipticalArc(arc) => arc.points_at_lengths(distances),
+            CurveRef::Parabola(parabola) => parabola.points_at_lengths(distances),
+            CurveRef::Hyperbola(hyperbola) => hyperbola.points_at_lengths(distances),
+            CurveRef::BSpline(spline) => spline.points_at_lengths(distances),
+            CurveRef::Nurbs(nurbs) => nurbs.points_at_lengths(distances),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::convert::arc_to_nurbs;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    const TOL: f64 = 1e-9;
+
+    fn close(a: Point2D, b: Point2D) -> bool {
+        a.distance_to(&b) < TOL
+    }
+
+    #[test]
+    fn test_polyline_divide_and_measure() {
+        let square = Polyline2D::closed(vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(2.0, 0.0),
+            Point2D::new(2.0, 2.0),
+            Point2D::new(0.0, 2.0),
+        ]);
+        assert!((ArcLength::length(&square) - 8.0).abs() < TOL);
+        assert!(close(square.point_at_length(3.0), Point2D::new(2.0, 1.0)));
+        // Closed curves wrap
+        assert!(close(square.point_at_length(-1.0), Point2D::new(0.0, 1.0)));
+
+        let corners = square.divide(4);
+        assert_eq!(corners.len(), 4);
+        for (point, corner) in corners.iter().zip(&square.vertices) {
+            assert!(close(*point, *corner));
+        }
+        assert_eq!(square.measure(2.0).len(), 4);
+        assert_eq!(square.measure(3.0).len(), 3);
+
+        let line = LineSegment2D::new(Point2D::new(0.0, 0.0), Point2D::new(10.0, 0.0));
+        let xs: Vec<f64> = line.measure(3.0).iter().map(|p| p.x).collect();
+        assert_eq!(xs, vec![0.0, 3.0, 6.0, 9.0]);
+        assert_eq!(line.divide(5).len(), 6);
+        assert!(close(line.point_at_length(12.0), line.end));
+    }
+
+    #[test]
+    fn test_circular_curves() {
+        let circle = Circle2D::new(Point2D::new(1.0, 1.0), 2.0);
+        assert!((circle.length() - 4.0 * PI).abs() < TOL);
+        assert!(close(circle.point_at_length(PI), Point2D::new(1.0, 3.0)));
+
+        let arc = Arc2D::new(Point2D::origin(), 1.0, 0.0, PI, false);
+        assert!(close(
+            arc.point_at_length(FRAC_PI_2),
+            Point2D::new(0.0, -1.0)
+        ));
+
+        // The exact NURBS form measures the same as the arc it came from
+        let quarter = Arc2D::new(Point2D::origin(), 3.0, 0.0, FRAC_PI_2, true);
+        let nurbs = arc_to_nurbs(&quarter);
+        assert!((nurbs.length() - ArcLength::length(&quarter)).abs() < TOL);
+        for (a, b) in nurbs.divide(6).iter().zip(quarter.divide(6)) {
+            assert!(close(*a, b), "{:?} vs {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_ellipse_length() {
+        let ellipse = Ellipse2D::new(Point2D::origin(), 2.0, 1.0, 0.0);
+        // Complete elliptic integral: 4a E(e) for a = 2, b = 1
+        let length = ellipse.length();
+        assert!((length - 9.688_448_220_547_675).abs() < 1e-9, "{}", length);
+        assert!(close(
+            ellipse.point_at_length(length / 4.0),
+            Point2D::new(0.0, 1.0)
+        ));
+        assert!(close(
+            ellipse.point_at_length(length / 2.0),
+            Point2D::new(-2.0, 0.0)
+        ));
+
+        // Equal steps along the curve, not equal angles
+        let points = ellipse.divide(12);
+        for pair in points.windows(2) {
+            let arc = EllipticalArc2D::new(
+                Point2D::origin(),
+                2.0,
+                1.0,
+                0.0,
+                pair[0].y.atan2(pair[0].x / 2.0),
+                pair[1].y.atan2(pair[1].x / 2.0),
+                true,
+            );
+            assert!((arc.length() - length / 12.0).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn test_parabola_length() {
+        // y = x² / 4: length f (T √(1 + T²) + asinh T) from the vertex to t = T
+        let exact = |t: f64| t * (1.0 + t * t).sqrt() + t.asinh();
+        let parabola = Parabola2D::new(Point2D::origin(), 1.0, std::f64::consts::FRAC_PI_2, -1.0, 3.0);
+        let length = parabola.length();
+        assert!((length - exact(3.0) - exact(1.0)).abs() < 1e-9, "{}", length);
+        assert!(close(parabola.point_at_length(exact(1.0)), Point2D::origin()));
+        assert!(close(parabola.point_at_length(length), parabola.end_point()));
+
+        let hyperbola = Hyperbola2D::new(Point2D::origin(), 1.0, 1.0, 0.0, -1.0, 1.0);
+        let half = Hyperbola2D::new(Point2D::origin(), 1.0, 1.0, 0.0, 0.0, 1.0);
+        assert!((hyperbola.length() - 2.0 * half.length()).abs() < 1e-9);
+        assert!(close(hyperbola.point_at_length(half.length()), Point2D::new(1.0, 0.0)));
+    }
+
+    #[test]
+    fn test_bezier_uneven_speed() {
+        // Straight, but the parameter bunches up near the start
+        let bezier = BezierCurve::cubic(
+            Point2D::new(0.0, 0.0),
+            Point2D::new(0.1, 0.0),
+            Point2D::new(0.2, 0.0),
+            Point2D::new(10.0, 0.0),
+        );
+        assert!((bezier.length() - 10.0).abs() < TOL);
+        for (i, point) in bezier.divide(5).iter().enumerate() {
+            assert!(
+                close(*point, Point2D::new(2.0 * i as f64, 0.0)),
+                "{:?}",
+                point
+            );
+        }
+
+        let nurbs = bezier.to_nurbs().unwrap();
+        let curve = CurveRef::from(&nurbs);
+        assert!((curve.length() - 10.0).abs() < TOL);
+        assert!(close(curve.point_at_length(7.5), Point2D::new(7.5, 0.0)));
+    }
+}
@@ -2,11 +2,31 @@
 //!
 //! This module provides complete mesh data structures including triangle meshes,
 //! quad meshes, and half-edge meshes for advanced topological operations.
+//!
+//! [`HalfEdgeMesh`] gives triangle soups read from STL or OBJ real
+//! topology: vertex and face adjacency, boundary loops around holes, and
+//! face and vertex normals for editing and repair tools.
 
+use crate::geometry::mass::SolidProperties;
 use nalgebra::{Point3, Vector3, Unit};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use rayon::prelude::*;
+use thiserror::Error;
+
+/// Errors building mesh topology
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MeshError {
+    #[error("Face {0} has fewer than three distinct vertices")]
+    DegenerateFace(usize),
+
+    #[error("Face references missing vertex {0}")]
+    InvalidVertex(usize),
+
+    #[error("Edge {0} -> {1} is used by more than one face in the same direction")]
+    NonManifoldEdge(usize, usize),
+}
+
+pub type MeshResult<T> = Result<T, MeshError>;
 
 /// A single vertex in 3D space
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -206,34 +226,52 @@ impl TriangleMesh {
     }
 
     /// Merges duplicate vertices within tolerance
+    ///
+    /// Vertices are bucketed in a grid of `tolerance`-sized cells, so only
+    /// neighbouring cells are searched and large triangle soups weld in
+    /// linear time. Faces that collapse are kept; drop them separately.
     pub fn merge_vertices(&mut self, tolerance: f64) {
-        let mut vertex_map: HashMap<usize, usize> = HashMap::new();
+        let tolerance = tolerance.max(f64::MIN_POSITIVE);
+        let cell = |p: &Point3<f64>| {
+            [p.x, p.y, p.z].map(|c| (c / tolerance).floor() as i64)
+        };
+        let mut grid: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        let mut vertex_map: Vec<usize> = Vec::with_capacity(self.vertices.len());
         let mut unique_vertices: Vec<Vertex> = Vec::new();
 
-        for (old_idx, vertex) in self.vertices.iter().enumerate() {
+        for vertex in &self.vertices {
+            let [x, y, z] = cell(&vertex.position);
             // Find if this vertex matches an existing one
             let mut found = None;
-            for (new_idx, unique) in unique_vertices.iter().enumerate() {
-                let dist = nalgebra::distance(&vertex.position, &unique.position);
-                if dist < tolerance {
-                    found = Some(new_idx);
-                    break;
+            'search: for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let Some(candidates) = grid.get(&[x + dx, y + dy, z + dz]) else {
+                            continue;
+                        };
+                        for &new_idx in candidates {
+                            let dist = nalgebra::distance(&vertex.position, &unique_vertices[new_idx].position);
+                            if dist < tolerance {
+                                found = Some(new_idx);
+                                break 'search;
+                            }
+                        }
+                    }
                 }
             }
 
-            if let Some(new_idx) = found {
-                vertex_map.insert(old_idx, new_idx);
-            } else {
-                let new_idx = unique_vertices.len();
+            let new_idx = found.unwrap_or_else(|| {
                 unique_vertices.push(*vertex);
-                vertex_map.insert(old_idx, new_idx);
-            }
+                grid.entry([x, y, z]).or_default().push(unique_vertices.len() - 1);
+                unique_vertices.len() - 1
+            });
+            vertex_map.push(new_idx);
         }
 
         // Remap faces
         for face in &mut self.faces {
             for vertex_idx in &mut face.vertices {
-                *vertex_idx = vertex_map[vertex_idx];
+                *vertex_idx = vertex_map[*vertex_idx];
             }
         }
 
@@ -258,6 +296,17 @@ impl TriangleMesh {
             .collect()
     }
 
+    /// Volume, centroid and inertia of the solid the mesh encloses, at a
+    /// mass per unit volume; `None` if it encloses no volume
+    pub fn mass_properties(&self, density: f64) -> Option<SolidProperties> {
+        let faces: Vec<Vec<Point3<f64>>> = self
+            .faces
+            .iter()
+            .map(|face| face.vertices.iter().map(|&i| self.vertices[i].position).collect())
+            .collect();
+        SolidProperties::from_faces(&faces, density)
+    }
+
     /// Subdivides each triangle into 4 smaller triangles
     pub fn subdivide(&mut self) {
         let mut new_vertices = self.vertices.clone();
@@ -429,65 +478,131 @@ impl HalfEdgeMesh {
     }
 
     /// Constructs a half-edge mesh from a triangle mesh
-    pub fn from_triangle_mesh(mesh: &TriangleMesh) -> Self {
-        let mut he_mesh = Self::new();
+    ///
+    /// Vertices must already be shared between triangles; weld a triangle
+    /// soup with [`TriangleMesh::merge_vertices`] first.
+    pub fn from_triangle_mesh(mesh: &TriangleMesh) -> MeshResult<Self> {
+        let positions = mesh.vertices.iter().map(|v| v.position).collect();
+        let faces: Vec<Vec<usize>> = mesh.faces.iter().map(|f| f.vertices.to_vec()).collect();
+        Self::from_polygons(positions, &faces)
+    }
 
-        // Create vertices
-        for vertex in &mesh.vertices {
-            he_mesh.vertices.push(HEVertex {
-                position: vertex.position,
+    /// Constructs a half-edge mesh from polygons given as vertex index loops
+    ///
+    /// Every edge gets a pair of half-edges: edges on the boundary of the
+    /// surface are paired with a half-edge that has no face, and those
+    /// boundary half-edges are linked into loops around each hole. Faces
+    /// must be consistently oriented and each directed edge may appear only
+    /// once, so an edge shared by three faces or by two faces of opposite
+    /// orientation is rejected.
+    pub fn from_polygons(positions: Vec<Point3<f64>>, faces: &[Vec<usize>]) -> MeshResult<Self> {
+        let mut mesh = Self::new();
+        mesh.vertices = positions
+            .into_iter()
+            .map(|position| HEVertex {
+                position,
                 half_edge: None,
-            });
-        }
-
-        let mut edge_map: HashMap<(usize, usize), usize> = HashMap::new();
-
-        // Create half-edges and faces
-        for (_face_idx, face) in mesh.faces.iter().enumerate() {
-            let he_face_idx = he_mesh.faces.len();
-            let first_he_idx = he_mesh.half_edges.len();
-
-            // Create three half-edges for the triangle
-            for i in 0..3 {
-                let v_start = face.vertices[i];
-                let v_end = face.vertices[(i + 1) % 3];
+            })
+            .collect();
 
-                let he_idx = he_mesh.half_edges.len();
+        let mut directed: HashMap<(usize, usize), usize> = HashMap::new();
+        for (face_idx, face) in faces.iter().enumerate() {
+            if face.len() < 3 {
+                return Err(MeshError::DegenerateFace(face_idx));
+            }
+            if let Some(&index) = face.iter().find(|&&v| v >= mesh.vertices.len()) {
+                return Err(MeshError::InvalidVertex(index));
+            }
 
-                he_mesh.half_edges.push(HalfEdge {
-                    vertex: v_end,
-                    next: first_he_idx + (i + 1) % 3,
-                    prev: first_he_idx + (i + 2) % 3,
+            let he_face_idx = mesh.faces.len();
+            let first = mesh.half_edges.len();
+            let n = face.len();
+            for i in 0..n {
+                let (from, to) = (face[i], face[(i + 1) % n]);
+                if from == to {
+                    return Err(MeshError::DegenerateFace(face_idx));
+                }
+                if directed.insert((from, to), first + i).is_some() {
+                    return Err(MeshError::NonManifoldEdge(from, to));
+                }
+                mesh.half_edges.push(HalfEdge {
+                    vertex: to,
+                    next: first + (i + 1) % n,
+                    prev: first + (i + n - 1) % n,
                     twin: None,
                     face: Some(he_face_idx),
                 });
+                mesh.vertices[from].half_edge.get_or_insert(first + i);
+            }
+            mesh.faces.push(HEFace { half_edge: first });
+        }
 
-                edge_map.insert((v_start, v_end), he_idx);
-
-                // Update vertex reference
-                if he_mesh.vertices[v_start].half_edge.is_none() {
-                    he_mesh.vertices[v_start].half_edge = Some(he_idx);
-                }
+        // Pair interior half-edges, adding a faceless twin on the boundary
+        let interior = mesh.half_edges.len();
+        for he in 0..interior {
+            if mesh.half_edges[he].twin.is_some() {
+                continue;
             }
+            let (from, to) = (mesh.source(he), mesh.half_edges[he].vertex);
+            let twin = match directed.get(&(to, from)) {
+                Some(&twin) => twin,
+                None => {
+                    mesh.half_edges.push(HalfEdge {
+                        vertex: from,
+                        next: usize::MAX,
+                        prev: usize::MAX,
+                        twin: Some(he),
+                        face: None,
+                    });
+                    mesh.half_edges.len() - 1
+                }
+            };
+            mesh.half_edges[he].twin = Some(twin);
+            mesh.half_edges[twin].twin = Some(he);
+        }
 
-            he_mesh.faces.push(HEFace {
-                half_edge: first_he_idx,
-            });
+        // A boundary half-edge ending at v continues with the boundary
+        // half-edge leaving v in the same fan of faces
+        for boundary in interior..mesh.half_edges.len() {
+            let v = mesh.half_edges[boundary].vertex;
+            // The twin leaves v inside a face; rotate through the fan
+            let mut out = mesh.half_edges[boundary].twin.unwrap_or(boundary);
+            while mesh.half_edges[out].face.is_some() {
+                let incoming = mesh.half_edges[out].prev;
+                out = mesh.half_edges[incoming].twin.unwrap_or(incoming);
+            }
+            mesh.half_edges[boundary].next = out;
+            mesh.half_edges[out].prev = boundary;
+            // Start boundary vertices' fans on the boundary
+            mesh.vertices[v].half_edge = Some(out);
         }
 
-        // Link twin half-edges
-        let edges: Vec<((usize, usize), usize)> = edge_map.iter().map(|(k, v)| (*k, *v)).collect();
+        Ok(mesh)
+    }
 
-        for ((v_start, v_end), he_idx) in edges {
-            if let Some(&twin_idx) = edge_map.get(&(v_end, v_start)) {
-                he_mesh.half_edges[he_idx].twin = Some(twin_idx);
-            }
+    /// Vertex a half-edge starts from
+    pub fn source(&self, he: usize) -> usize {
+        match self.half_edges[he].twin {
+            Some(twin) => self.half_edges[twin].vertex,
+            None => self.half_edges[self.half_edges[he].prev].vertex,
         }
+    }
+
+    /// Number of edges (half-edge pairs)
+    pub fn edge_count(&self) -> usize {
+        self.half_edges.iter().enumerate().filter(|(i, he)| he.twin.is_none_or(|t| t > *i)).count()
+    }
 
-        he_mesh
+    /// Euler characteristic V - E + F; 2 for a closed surface of genus 0
+    pub fn euler_characteristic(&self) -> i64 {
+        self.vertices.len() as i64 - self.edge_count() as i64 + self.faces.len() as i64
     }
 
-    /// Gets all half-edges around a vertex
+    /// Gets all half-edges leaving a vertex, clockwise around it as seen
+    /// from the front of counter-clockwise faces
+    ///
+    /// For a vertex on the boundary the first is the boundary half-edge
+    /// leaving it.
     pub fn vertex_half_edges(&self, vertex_idx: usize) -> Vec<usize> {
         let mut result = Vec::new();
 
@@ -497,14 +612,13 @@ impl HalfEdgeMesh {
             loop {
                 result.push(current);
 
-                // Move to next outgoing half-edge
-                if let Some(twin) = self.half_edges[current].twin {
-                    current = self.half_edges[twin].next;
-                    if current == start_he {
-                        break;
+                // Move to the next outgoing half-edge around the vertex
+                let incoming = self.half_edges[current].prev;
+                match self.half_edges[incoming].twin {
+                    Some(twin) if twin != start_he && result.len() < self.half_edges.len() => {
+                        current = twin;
                     }
-                } else {
-                    break; // Boundary vertex
+                    _ => break,
                 }
             }
         }
@@ -517,14 +631,175 @@ impl HalfEdgeMesh {
         self.vertex_half_edges(vertex_idx).len()
     }
 
+    /// Vertices joined to a vertex by an edge, in order around it
+    pub fn vertex_neighbors(&self, vertex_idx: usize) -> Vec<usize> {
+        self.vertex_half_edges(vertex_idx)
+            .into_iter()
+            .map(|he| self.half_edges[he].vertex)
+            .collect()
+    }
+
+    /// Faces around a vertex, in order around it
+    pub fn vertex_faces(&self, vertex_idx: usize) -> Vec<usize> {
+        self.vertex_half_edges(vertex_idx)
+            .into_iter()
+            .filter_map(|he| self.half_edges[he].face)
+            .collect()
+    }
+
+    /// Half-edges around a face, starting from its stored half-edge
+    pub fn face_half_edges(&self, face_idx: usize) -> Vec<usize> {
+        self.loop_half_edges(self.faces[face_idx].half_edge)
+    }
+
+    /// Vertices of a face, in order
+    pub fn face_vertices(&self, face_idx: usize) -> Vec<usize> {
+        self.face_half_edges(face_idx)
+            .into_iter()
+            .map(|he| self.source(he))
+            .collect()
+    }
+
+    /// Faces sharing an edge with a face
+    pub fn face_neighbors(&self, face_idx: usize) -> Vec<usize> {
+        self.face_half_edges(face_idx)
+            .into_iter()
+            .filter_map(|he| self.half_edges[he].twin.and_then(|t| self.half_edges[t].face))
+            .collect()
+    }
+
+    /// Whether a half-edge or its twin lies on the boundary
+    pub fn is_boundary_edge(&self, he: usize) -> bool {
+        self.half_edges[he].face.is_none()
+            || self.half_edges[he]
+                .twin
+                .is_none_or(|t| self.half_edges[t].face.is_none())
+    }
+
+    /// Whether a vertex lies on the boundary (or is isolated)
+    pub fn is_boundary_vertex(&self, vertex_idx: usize) -> bool {
+        self.vertices[vertex_idx]
+            .half_edge
+            .is_none_or(|he| self.half_edges[he].face.is_none())
+    }
+
+    /// Whether the surface has no boundary
+    pub fn is_closed(&self) -> bool {
+        self.half_edges.iter().all(|he| he.face.is_some())
+    }
+
+    /// Boundary loops as vertex index lists
+    ///
+    /// Each loop runs along a hole (or the outer edge of an open surface)
+    /// with the surface on its right, that is clockwise seen from the
+    /// side the face normals point to.
+    pub fn boundary_loops(&self) -> Vec<Vec<usize>> {
+        let mut visited = vec![false; self.half_edges.len()];
+        let mut loops = Vec::new();
+        for start in 0..self.half_edges.len() {
+            if visited[start] || self.half_edges[start].face.is_some() {
+                continue;
+            }
+            let half_edges = self.loop_half_edges(start);
+            for &he in &half_edges {
+                visited[he] = true;
+            }
+            loops.push(half_edges.into_iter().map(|he| self.source(he)).collect());
+        }
+        loops
+    }
+
+    /// Unit normal of a face, by Newell's method so non-planar polygons get
+    /// a best-fit normal
+    pub fn face_normal(&self, face_idx: usize) -> Unit<Vector3<f64>> {
+        let area = self.face_area_vector(face_idx);
+        if area.norm() < 1e-20 {
+            Unit::new_unchecked(Vector3::z())
+        } else {
+            Unit::new_normalize(area)
+        }
+    }
+
+    /// Area of a face
+    pub fn face_area(&self, face_idx: usize) -> f64 {
+        self.face_area_vector(face_idx).norm()
+    }
+
+    /// Vertex normals, the area-weighted average of the faces around each
+    /// vertex; isolated vertices get +Z
+    pub fn vertex_normals(&self) -> Vec<Unit<Vector3<f64>>> {
+        let mut sums = vec![Vector3::zeros(); self.vertices.len()];
+        for face_idx in 0..self.faces.len() {
+            let area = self.face_area_vector(face_idx);
+            for v in self.face_vertices(face_idx) {
+                sums[v] += area;
+            }
+        }
+        sums.into_iter()
+            .map(|n| {
+                if n.norm() < 1e-20 {
+                    Unit::new_unchecked(Vector3::z())
+                } else {
+                    Unit::new_normalize(n)
+                }
+            })
+            .collect()
+    }
+
+    /// Triangle mesh with vertex normals; polygons are fanned from their
+    /// first vertex
+    pub fn to_triangle_mesh(&self) -> TriangleMesh {
+        let vertices = self
+            .vertices
+            .iter()
+            .zip(self.vertex_normals())
+            .map(|(v, normal)| Vertex {
+                position: v.position,
+                normal: Some(normal),
+                uv: None,
+            })
+            .collect();
+        let mut faces = Vec::new();
+        for face_idx in 0..self.faces.len() {
+            let loop_ = self.face_vertices(face_idx);
+            for i in 1..loop_.len() - 1 {
+                faces.push(TriangleFace::new(loop_[0], loop_[i], loop_[i + 1]));
+            }
+        }
+        TriangleMesh::from_data(vertices, faces)
+    }
+
+    /// Twice the area times the unit normal (Newell's method)
+    fn face_area_vector(&self, face_idx: usize) -> Vector3<f64> {
+        let loop_ = self.face_vertices(face_idx);
+        let mut sum = Vector3::zeros();
+        for (i, &v) in loop_.iter().enumerate() {
+            let p = self.vertices[v].position.coords;
+            let q = self.vertices[loop_[(i + 1) % loop_.len()]].position.coords;
+            sum += p.cross(&q);
+        }
+        sum / 2.0
+    }
+
+    /// Half-edges in the `next` cycle starting at `start`
+    fn loop_half_edges(&self, start: usize) -> Vec<usize> {
+        let mut result = vec![start];
+        let mut current = self.half_edges[start].next;
+        while current != start && result.len() < self.half_edges.len() {
+            result.push(current);
+            current = self.half_edges[current].next;
+        }
+        result
+    }
+
     /// Performs Catmull-Clark subdivision
     pub fn catmull_clark_subdivide(&self) -> Self {
         let mut new_mesh = Self::new();
 
         // Step 1: Face points (centroid of each face)
         let mut face_points = Vec::new();
-        for face in &self.faces {
-            let vertices = self.face_vertices(face.half_edge);
+        for face_idx in 0..self.faces.len() {
+            let vertices = self.face_vertices(face_idx);
             let centroid = self.compute_centroid(&vertices);
             face_points.push(centroid);
         }
@@ -578,22 +853,6 @@ impl HalfEdgeMesh {
         new_mesh
     }
 
-    /// Gets vertices of a face
-    fn face_vertices(&self, start_he: usize) -> Vec<usize> {
-        let mut vertices = Vec::new();
-        let mut current = start_he;
-
-        loop {
-            vertices.push(self.half_edges[current].vertex);
-            current = self.half_edges[current].next;
-            if current == start_he {
-                break;
-            }
-        }
-
-        vertices
-    }
-
     /// Computes centroid of vertices
     fn compute_centroid(&self, vertex_indices: &[usize]) -> Point3<f64> {
         let mut sum = Vector3::zeros();
@@ -758,4 +1017,83 @@ mod tests {
 
         assert_eq!(mesh.faces.len(), original_faces * 4);
     }
+    /// Unit square split into two triangles, with a duplicate corner
+    fn square_soup() -> TriangleMesh {
+        let p = |x: f64, y: f64| Vertex::new(Point3::new(x, y, 0.0));
+        TriangleMesh::from_data(
+            vec![p(0.0, 0.0), p(1.0, 0.0), p(1.0, 1.0), p(0.0, 0.0), p(1.0, 1.0), p(0.0, 1.0)],
+            vec![TriangleFace::new(0, 1, 2), TriangleFace::new(3, 4, 5)],
+        )
+    }
+
+    #[test]
+    fn test_half_edge_open_surface() {
+        let mut soup = square_soup();
+        soup.merge_vertices(1e-9);
+        assert_eq!(soup.vertices.len(), 4);
+
+        let mesh = HalfEdgeMesh::from_triangle_mesh(&soup).unwrap();
+        assert_eq!(mesh.edge_count(), 5);
+        assert_eq!(mesh.euler_characteristic(), 1);
+        assert!(!mesh.is_closed());
+
+        // The diagonal is shared, the rest is boundary
+        assert_eq!(mesh.face_neighbors(0), vec![1]);
+        assert_eq!(mesh.vertex_valence(0), 3);
+        // Clockwise from the boundary edge leaving the vertex
+        assert_eq!(mesh.vertex_neighbors(0), vec![3, 1, 2]);
+        assert_eq!(mesh.vertex_faces(0), vec![0, 1]);
+        assert!(mesh.is_boundary_vertex(0));
+
+        // One hole-free loop around the outside, clockwise seen from +Z
+        let loops = mesh.boundary_loops();
+        assert_eq!(loops.len(), 1);
+        let mut loop_ = loops[0].clone();
+        let first = loop_.iter().position(|&v| v == 0).unwrap();
+        loop_.rotate_left(first);
+        assert_eq!(loop_, vec![0, 3, 2, 1]);
+
+        assert!((mesh.face_area(0) - 0.5).abs() < 1e-12);
+        assert!(mesh.vertex_normals().iter().all(|n| (n.z - 1.0).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_half_edge_closed_surface() {
+        // Tetrahedron with outward-facing triangles
+        let positions = vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ];
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3], vec![0, 3, 2]];
+        let mesh = HalfEdgeMesh::from_polygons(positions.clone(), &faces).unwrap();
+        assert!(mesh.is_closed());
+        assert!(mesh.boundary_loops().is_empty());
+        assert_eq!(mesh.euler_characteristic(), 2);
+        for v in 0..4 {
+            assert_eq!(mesh.vertex_valence(v), 3);
+            assert_eq!(mesh.vertex_faces(v).len(), 3);
+        }
+        assert_eq!(mesh.face_vertices(2), vec![1, 2, 3]);
+        assert!((mesh.face_normal(0).z + 1.0).abs() < 1e-12);
+        // Vertex normals point away from the centroid
+        let centroid = Point3::new(0.25, 0.25, 0.25);
+        for (v, normal) in mesh.vertex_normals().iter().enumerate() {
+            assert!((positions[v] - centroid).dot(normal) > 0.0);
+        }
+        assert_eq!(mesh.to_triangle_mesh().faces.len(), 4);
+
+        // Flipping one face makes its edges clash with the neighbours
+        let mut flipped = faces.clone();
+        flipped[3].reverse();
+        assert!(matches!(
+            HalfEdgeMesh::from_polygons(positions.clone(), &flipped),
+            Err(MeshError::NonManifoldEdge(..))
+        ));
+        assert_eq!(
+            HalfEdgeMesh::from_polygons(positions, &[vec![0, 1, 7]]),
+            Err(MeshError::InvalidVertex(7))
+        );
+    }
 }
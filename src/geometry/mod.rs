@@ -7,36 +7,126 @@
 //! - Points with CAD-specific operations
 //! - Lines, line segments, and polylines
 //! - Arcs, circles, and ellipses
+//! - Parabolas, hyperbolas and implicit conics, with conic-line and
+//!   conic-conic intersection
 //! - Bezier curves, B-splines, and NURBS
+//! - Line/arc fitting, spline control point reduction and spline-to-arc
+//!   conversion
+//! - Chord-tolerance flattening, biarc approximation and exact NURBS forms
+//!   of conics and Bezier curves
+//! - Offset curves with self-intersection trimming, corner joins and end caps
+//! - Fillets and chamfers between lines and arcs
+//! - Arc-length measurement: length, point at distance, divide and measure
+//!   along any curve
+//! - Closest points and distances from any entity to a point or another
+//!   entity
+//! - Curve-curve intersection across lines, arcs, conics and splines, with
+//!   tangent contacts reported once
+//! - Curvature analysis of splines and NURBS: signed curvature, curvature
+//!   combs and inflection points
+//! - Douglas-Peucker simplification and curvature-flow smoothing of noisy
+//!   polylines (scans, traced images, GPS tracks)
 //! - Polygons with advanced algorithms
+//! - Area, centroid and second moments of area of regions, and volume,
+//!   centroid and inertia tensors of closed meshes, with density
+//! - Clipping of lines, arcs, polylines and filled regions against convex and
+//!   non-convex windows
+//! - Convex hulls of point sets in 2D and 3D
+//! - Minimum oriented bounding rectangles and boxes, and smallest enclosing
+//!   circles and spheres
+//! - Constrained Delaunay triangulation and Voronoi diagrams
+//! - Hatch patterns with island detection
+//! - Boundary detection: the enclosed region and its islands around a pick
+//!   point
+//! - Ear-clipping tessellation of polygons with holes for filled rendering
+//! - Chord, angle and edge length tolerances for faceting curved geometry
+//! - Bounding volume hierarchy for box, nearest and ray queries over entities
 //!
 //! ## 3D Geometry
 //! - 3D solid primitives (Box, Sphere, Cylinder, Cone, Torus, Wedge)
 //! - Parametric surfaces (Plane, Bezier, B-Spline, NURBS)
-//! - Mesh data structures (Triangle, Quad, Half-Edge meshes)
+//! - Mesh data structures (Triangle, Quad, Half-Edge meshes), with adjacency,
+//!   boundary loops and normals on half-edge meshes
+//! - Quadric error decimation, isotropic remeshing and display levels of
+//!   detail for large meshes
+//! - Mesh repair for 3D printing: welding, hole filling, non-manifold fixes
+//!   and consistent outward winding
 //! - CSG operations (Union, Subtraction, Intersection)
 //! - Extrusion operations (Linear, Revolution, Sweep, Loft)
+//! - Point clouds with voxel-grid downsampling and spatial chunking
+//! - Scene-wide ray casting against meshes, solids and tessellated surfaces,
+//!   reporting the entity, face and distance of the nearest hit
 
 // 2D Geometry modules
 pub mod arc;
+pub mod boundary;
+pub mod clip;
+pub mod closest;
+pub mod conic;
+pub mod convert;
+pub mod curvature;
 pub mod curve;
+pub mod delaunay;
+pub mod enclose;
+pub mod fillet;
+pub mod fitting;
+pub mod hatch;
+pub mod hull;
+pub mod intersect;
 pub mod line;
+pub mod mass;
+pub mod measure;
+pub mod offset;
 pub mod point;
 pub mod polygon;
+pub mod simplify;
+pub mod spatial;
+pub mod tessellate;
 
 // 3D Geometry modules
 pub mod solid;
 pub mod surface;
 pub mod mesh;
+pub mod decimate;
+pub mod remesh;
 pub mod boolean;
 pub mod extrude;
+pub mod pointcloud;
+pub mod raycast;
+pub mod repair;
 
 // Re-export commonly used 2D types
 pub use arc::{Arc2D, Circle2D, Ellipse2D, EllipticalArc2D};
-pub use curve::{BezierCurve, BSpline, NurbsCurve};
+pub use boundary::{boundary_at, BoundaryOptions, Region};
+pub use clip::{CircleClip, ClipSide, ClipWindow};
+pub use closest::{closest_between, Closest, ClosestPair, ClosestPoint};
+pub use conic::{Conic2D, ConicArc, ConicKind, Hyperbola2D, Parabola2D};
+pub use convert::{
+    arc_to_nurbs, biarcs, circle_to_nurbs, ellipse_to_nurbs, elliptical_arc_to_nurbs, flatten,
+    hyperbola_to_nurbs, parabola_to_nurbs, to_arc_polyline,
+};
+pub use curvature::{Curvature, CurvatureAnalysis, CurvatureSample, Inflection};
+pub use curve::{BezierCurve, BSpline, KnotParameterization, NurbsCurve, SplineFit};
+pub use delaunay::{Triangulation, VoronoiCell};
+pub use enclose::{
+    enclosing_circle, enclosing_sphere, min_area_rect, min_volume_box, OrientedBox3D, OrientedRect2D,
+};
+pub use fillet::{chamfer, fillet, Corner};
+pub use fitting::{ArcPolyline, ArcVertex, FitSegment};
+pub use hatch::{hatch_lines, HatchPattern, HatchStyle, PatternLine};
+pub use hull::{convex_hull_2d, convex_hull_3d, ConvexHull3D};
+pub use intersect::{
+    intersect, intersect_with_tolerance, intersect_within, CurveRef, Intersection, IntersectionKind,
+};
 pub use line::{Line2D, LineSegment2D, Polyline2D};
+pub use mass::{AreaProperties, SolidProperties};
+pub use measure::ArcLength;
+pub use offset::{offset, CapStyle, JoinStyle, OffsetOptions};
 pub use point::Point2D;
 pub use polygon::Polygon2D;
+pub use simplify::{curvature, simplify, simplify_indices, smooth, SmoothOptions};
+pub use spatial::SpatialIndex;
+pub use tessellate::{tessellate, FillMesh, TessellationSettings};
 
 // Re-export commonly used 3D types
 pub use solid::{
@@ -49,8 +139,8 @@ pub use surface::{
 };
 
 pub use mesh::{
-    HalfEdge, HEFace, HEVertex, HalfEdgeMesh, MeshSimplifier, QuadFace, QuadMesh,
-    TriangleFace, TriangleMesh, Vertex,
+    HalfEdge, HEFace, HEVertex, HalfEdgeMesh, MeshError, MeshResult, MeshSimplifier, QuadFace,
+    QuadMesh, TriangleFace, TriangleMesh, Vertex,
 };
 
 pub use boolean::{BooleanOperation, CSGNode, CSGOperator, CoplanarHandler};
@@ -58,3 +148,9 @@ pub use boolean::{BooleanOperation, CSGNode, CSGOperator, CoplanarHandler};
 pub use extrude::{
     LinearExtrude, Loft, Path3D, Profile2D, Revolution, Sweep,
 };
+
+pub use pointcloud::{CloudChunk, PointCloud};
+pub use raycast::{MeshTarget, RayCaster, RayHit, RayTarget, SurfaceTarget};
+pub use decimate::{Decimated, Decimator, MeshLod};
+pub use remesh::Remesher;
+pub use repair::{MeshDiagnostics, MeshRepair, RepairReport};
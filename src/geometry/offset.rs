@@ -0,0 +1,802 @@
+//! Offset curves
+//!
+//! [`offset`] builds the curve at a constant distance from a polyline of
+//! lines and arcs. Every segment is moved sideways, neighbours are joined
+//! (trimmed on the inside of a corner, mitered, beveled or rounded on the
+//! outside), and the raw result is split wherever it crosses itself. Pieces
+//! that come closer to the original than the offset distance are dropped
+//! and the rest are chained back together, so one input can give several
+//! curves (an inward offset through a narrow waist) or none (an inward
+//! offset wider than the shape).
+//!
+//! An open curve can instead be offset to both sides and closed with end
+//! caps, which gives the outline of a stroke. Splines are converted to
+//! lines and arcs first ([`offset_spline`], [`offset_nurbs`]).
+
+use crate::core::precision::EPSILON;
+use crate::geometry::arc::Arc2D;
+use crate::geometry::curve::{BSpline, NurbsCurve};
+use crate::geometry::fitting::{nurbs_to_arcs, spline_to_arcs, ArcPolyline, ArcVertex, FitSegment};
+use crate::geometry::line::{LineSegment2D, Polyline2D};
+use crate::geometry::point::Point2D;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Relative tolerance for matching points and discarding slivers
+const RELATIVE_TOLERANCE: f64 = 1e-8;
+
+/// Piece of the raw offset, with the corner it cuts across if it is a
+/// bevel or butt cap; such pieces may come as close to that corner as they
+/// lie and still be kept
+type RawPiece = (FitSegment, Option<Point2D>);
+
+/// Treatment of the outside of a corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum JoinStyle {
+    /// Extend the neighbouring segments until they meet; corners sharper
+    /// than the miter limit are beveled
+    #[default]
+    Miter,
+    /// Arc around the corner
+    Round,
+    /// Straight line across the corner
+    Bevel,
+}
+
+/// Treatment of the ends of a two-sided offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CapStyle {
+    /// Square end through the end point
+    #[default]
+    Butt,
+    /// Half circle around the end point
+    Round,
+    /// Square end extended past the end point by the offset distance
+    Square,
+}
+
+/// Offset settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OffsetOptions {
+    /// Outside corner treatment
+    pub join: JoinStyle,
+    /// Longest miter, from corner to tip, as a multiple of the distance
+    pub miter_limit: f64,
+    /// Offset open curves to both sides, closing the ends with this cap
+    pub cap: Option<CapStyle>,
+}
+
+impl Default for OffsetOptions {
+    fn default() -> Self {
+        Self {
+            join: JoinStyle::Miter,
+            miter_limit: 4.0,
+            cap: None,
+        }
+    }
+}
+
+impl OffsetOptions {
+    /// Set the outside corner treatment
+    pub fn with_join(mut self, join: JoinStyle) -> Self {
+        self.join = join;
+        self
+    }
+
+    /// Set the miter limit
+    pub fn with_miter_limit(mut self, limit: f64) -> Self {
+        self.miter_limit = limit;
+        self
+    }
+
+    /// Offset to both sides and cap the ends
+    pub fn with_cap(mut self, cap: CapStyle) -> Self {
+        self.cap = Some(cap);
+        self
+    }
+}
+
+/// Offset a line/arc polyline
+///
+/// Positive distances offset to the left of the direction of travel, which
+/// is inward for a counterclockwise closed curve. With a cap set, an open
+/// curve is offset by the absolute distance to both sides and the result is
+/// a closed outline.
+pub fn offset(path: &ArcPolyline, distance: f64, options: &OffsetOptions) -> Vec<ArcPolyline> {
+    let source: Vec<FitSegment> = path
+        .segments()
+        .into_iter()
+        .filter(|segment| segment.length() > EPSILON)
+        .collect();
+    if source.is_empty() {
+        return Vec::new();
+    }
+    if distance.abs() < EPSILON {
+        return vec![path.clone()];
+    }
+
+    let extent = path
+        .vertices
+        .iter()
+        .map(|v| v.point.x.abs().max(v.point.y.abs()))
+        .fold(0.0, f64::max);
+    let tol = RELATIVE_TOLERANCE * (extent + distance.abs()).max(1.0);
+
+    let (raw, distance) = match options.cap {
+        Some(cap) if !path.closed => {
+            let d = distance.abs();
+            let reversed: Vec<FitSegment> = source.iter().rev().map(reverse).collect();
+            let (first, last) = (source[0], source[source.len() - 1]);
+            let mut raw = offset_run(&source, d, options, false, tol);
+            raw.extend(cap_pieces(last.end(), end_tangent(&last), d, cap));
+            raw.extend(offset_run(&reversed, d, options, false, tol));
+            raw.extend(cap_pieces(first.start(), -start_tangent(&first), d, cap));
+            (raw, d)
+        }
+        _ => (offset_run(&source, distance, options, path.closed, tol), distance),
+    };
+
+    let pieces = clip(&raw, &source, distance.abs(), tol);
+    chain(pieces, tol)
+        .into_iter()
+        .map(|(segments, closed)| ArcPolyline::from_segments(&segments, closed))
+        .collect()
+}
+
+/// Offset a straight-segment polyline
+pub fn offset_polyline(
+    polyline: &Polyline2D,
+    distance: f64,
+    options: &OffsetOptions,
+) -> Vec<ArcPolyline> {
+    let path = ArcPolyline {
+        vertices: polyline
+            .vertices
+            .iter()
+            .map(|&point| ArcVertex { point, bulge: 0.0 })
+            .collect(),
+        closed: polyline.closed,
+    };
+    offset(&path, distance, options)
+}
+
+/// Offset a single arc
+pub fn offset_arc(arc: &Arc2D, distance: f64, options: &OffsetOptions) -> Vec<ArcPolyline> {
+    offset(
+        &ArcPolyline::from_segments(&[FitSegment::Arc(*arc)], false),
+        distance,
+        options,
+    )
+}
+
+/// Offset a B-spline, approximated by lines and arcs within `tolerance`
+pub fn offset_spline(
+    spline: &BSpline,
+    distance: f64,
+    tolerance: f64,
+    options: &OffsetOptions,
+) -> Vec<ArcPolyline> {
+    offset(&spline_to_arcs(spline, tolerance), distance, options)
+}
+
+/// Offset a NURBS curve, approximated by lines and arcs within `tolerance`
+pub fn offset_nurbs(
+    curve: &NurbsCurve,
+    distance: f64,
+    tolerance: f64,
+    options: &OffsetOptions,
+) -> Vec<ArcPolyline> {
+    offset(&nurbs_to_arcs(curve, tolerance), distance, options)
+}
+
+/// Move every segment sideways and join the neighbours
+fn offset_run(
+    segments: &[FitSegment],
+    distance: f64,
+    options: &OffsetOptions,
+    closed: bool,
+    tol: f64,
+) -> Vec<RawPiece> {
+    let mut moved: Vec<FitSegment> = segments
+        .iter()
+        .map(|segment| offset_segment(segment, distance, tol))
+        .collect();
+    let count = moved.len();
+    let mut joins: Vec<Vec<RawPiece>> = vec![Vec::new(); count];
+
+    let join_count = if closed { count } else { count - 1 };
+    for i in 0..join_count {
+        let j = (i + 1) % count;
+        let (a_end, b_start) = (moved[i].end(), moved[j].start());
+        if a_end.distance_to(&b_start) <= tol {
+            continue;
+        }
+        let vertex = segments[i].end();
+        let (ta, tb) = (end_tangent(&segments[i]), start_tangent(&segments[j]));
+
+        if ta.cross(&tb) * distance > 0.0 {
+            // Inside of the corner: cut both segments back to where they cross
+            let crossing = crossings(&moved[i], &moved[j], tol)
+                .into_iter()
+                .max_by(|x, y| x.0.total_cmp(&y.0));
+            match crossing {
+                Some((sa, sb)) if i != j => {
+                    moved[i] = sub_segment(&moved[i], 0.0, sa);
+                    moved[j] = sub_segment(&moved[j], sb, 1.0);
+                }
+                // No crossing: detour through the corner; clipping removes it
+                _ => {
+                    joins[i] = vec![(line(a_end, vertex), None), (line(vertex, b_start), None)];
+                }
+            }
+        } else {
+            joins[i] = outside_join(vertex, a_end, b_start, ta, tb, distance, options);
+        }
+    }
+
+    moved
+        .into_iter()
+        .zip(joins)
+        .flat_map(|(segment, join)| std::iter::once((segment, None)).chain(join))
+        .filter(|(segment, _)| segment.length() > tol)
+        .collect()
+}
+
+fn offset_segment(segment: &FitSegment, distance: f64, tol: f64) -> FitSegment {
+    match segment {
+        FitSegment::Line(l) => {
+            let n = left_normal(start_tangent(segment));
+            line(l.start + n * distance, l.end + n * distance)
+        }
+        FitSegment::Arc(arc) => {
+            // The left side of a counterclockwise arc faces its center
+            let radius = if arc.ccw {
+                arc.radius - distance
+            } else {
+                arc.radius + distance
+            };
+            if radius > tol {
+                FitSegment::Arc(Arc2D { radius, ..*arc })
+            } else {
+                // Collapsed past its center; always closer than the distance
+                let (ns, ne) = (
+                    left_normal(start_tangent(segment)),
+                    left_normal(end_tangent(segment)),
+                );
+                line(
+                    segment.start() + ns * distance,
+                    segment.end() + ne * distance,
+                )
+            }
+        }
+    }
+}
+
+fn outside_join(
+    vertex: Point2D,
+    a_end: Point2D,
+    b_start: Point2D,
+    ta: Point2D,
+    tb: Point2D,
+    distance: f64,
+    options: &OffsetOptions,
+) -> Vec<RawPiece> {
+    let bevel = vec![(line(a_end, b_start), Some(vertex))];
+    match options.join {
+        JoinStyle::Round => vec![(
+            FitSegment::Arc(Arc2D::new(
+                vertex,
+                distance.abs(),
+                angle_of(a_end - vertex),
+                angle_of(b_start - vertex),
+                distance < 0.0,
+            )),
+            None,
+        )],
+        JoinStyle::Bevel => bevel,
+        JoinStyle::Miter => {
+            // Tip where the tangent extensions meet: ahead of a, behind b
+            let denom = ta.cross(&tb);
+            let tip = (denom.abs() > EPSILON)
+                .then(|| {
+                    let s = (b_start - a_end).cross(&tb) / denom;
+                    let u = (b_start - a_end).cross(&ta) / denom;
+                    (s >= 0.0 && u <= 0.0).then(|| a_end + ta * s)
+                })
+                .flatten()
+                .filter(|tip| tip.distance_to(&vertex) <= options.miter_limit * distance.abs());
+            match tip {
+                Some(tip) => vec![(line(a_end, tip), None), (line(tip, b_start), None)],
+                None => bevel,
+            }
+        }
+    }
+}
+
+/// Pieces closing the end of a two-sided offset at `point`, from the left
+/// side of `tangent` around to the right
+fn cap_pieces(point: Point2D, tangent: Point2D, distance: f64, cap: CapStyle) -> Vec<RawPiece> {
+    let n = left_normal(tangent) * distance;
+    let (left, right) = (point + n, point - n);
+    match cap {
+        CapStyle::Butt => vec![(line(left, right), Some(point))],
+        CapStyle::Round => vec![(
+            FitSegment::Arc(Arc2D::new(point, distance, angle_of(n), angle_of(-n), false)),
+            None,
+        )],
+        CapStyle::Square => {
+            let ahead = tangent * distance;
+            vec![
+                (line(left, left + ahead), None),
+                (line(left + ahead, right + ahead), None),
+                (line(right + ahead, right), None),
+            ]
+        }
+    }
+}
+
+/// Split the raw offset where it crosses itself and keep the pieces that
+/// stay at least `distance` from the source
+fn clip(raw: &[RawPiece], source: &[FitSegment], distance: f64, tol: f64) -> Vec<FitSegment> {
+    let mut cuts: Vec<Vec<f64>> = vec![Vec::new(); raw.len()];
+    for i in 0..raw.len() {
+        for j in i + 1..raw.len() {
+            let (a, b) = (&raw[i].0, &raw[j].0);
+            for (ti, tj) in crossings(a, b, tol) {
+                cuts[i].push(ti);
+                cuts[j].push(tj);
+            }
+            let (on_i, on_j) = overlaps(a, b, tol);
+            cuts[i].extend(on_i);
+            cuts[j].extend(on_j);
+        }
+    }
+
+    let mut kept: Vec<FitSegment> = Vec::new();
+    for ((segment, anchor), mut params) in raw.iter().zip(cuts) {
+        params.retain(|t| *t > EPSILON && *t < 1.0 - EPSILON);
+        params.push(0.0);
+        params.push(1.0);
+        params.sort_by(f64::total_cmp);
+        params.dedup_by(|a, b| (*a - *b).abs() < EPSILON);
+
+        for pair in params.windows(2) {
+            let piece = sub_segment(segment, pair[0], pair[1]);
+            if piece.length() <= tol {
+                continue;
+            }
+            let probe = point_at(&piece, 0.5);
+            let required = anchor.map_or(distance, |a| distance.min(probe.distance_to(&a)));
+            let clearance = source
+                .iter()
+                .map(|s| distance_to(s, &probe))
+                .fold(f64::INFINITY, f64::min);
+            // Collinear overlaps leave identical pieces; keep one
+            let duplicate = kept.iter().any(|k| {
+                k.start().distance_to(&piece.start()) <= tol
+                    && k.end().distance_to(&piece.end()) <= tol
+                    && point_at(k, 0.5).distance_to(&probe) <= tol
+            });
+            if clearance >= required - tol && !duplicate {
+                kept.push(piece);
+            }
+        }
+    }
+    kept
+}
+
+/// Link pieces end to start, preferring the next piece in raw order
+fn chain(pieces: Vec<FitSegment>, tol: f64) -> Vec<(Vec<FitSegment>, bool)> {
+    let count = pieces.len();
+    let meets = |a: &FitSegment, b: &FitSegment| a.end().distance_to(&b.start()) <= 4.0 * tol;
+    // Start where a chain begins, not partway round a loop
+    let first = (0..count)
+        .find(|&i| !meets(&pieces[(i + count - 1) % count], &pieces[i]))
+        .unwrap_or(0);
+
+    let mut used = vec![false; count];
+    let mut chains = Vec::new();
+    for step in 0..count {
+        let start = (first + step) % count;
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+        let mut run = vec![pieces[start]];
+        let mut at = start;
+        let closed = loop {
+            let last = run[run.len() - 1];
+            if run.len() > 1 && meets(&last, &run[0]) {
+                break true;
+            }
+            let next = (1..count)
+                .map(|o| (at + o) % count)
+                .find(|&c| !used[c] && meets(&last, &pieces[c]));
+            match next {
+                Some(c) => {
+                    used[c] = true;
+                    run.push(pieces[c]);
+                    at = c;
+                }
+                None => break false,
+            }
+        };
+        chains.push((merge(run, closed, tol), closed));
+    }
+    chains
+}
+
+/// Join consecutive collinear lines and co-circular arcs
+fn merge(run: Vec<FitSegment>, closed: bool, tol: f64) -> Vec<FitSegment> {
+    let mut merged: Vec<FitSegment> = Vec::with_capacity(run.len());
+    for segment in run {
+        match merged.last().and_then(|last| combine(last, &segment, tol)) {
+            Some(joined) => *merged.last_mut().unwrap() = joined,
+            None => merged.push(segment),
+        }
+    }
+    if closed && merged.len() > 2 {
+        if let Some(joined) = combine(&merged[merged.len() - 1], &merged[0], tol) {
+            merged[0] = joined;
+            merged.pop();
+        }
+    }
+    merged
+}
+
+fn combine(a: &FitSegment, b: &FitSegment, tol: f64) -> Option<FitSegment> {
+    match (a, b) {
+        (FitSegment::Line(p), FitSegment::Line(q)) => {
+            let (u, v) = (start_tangent(a), start_tangent(b));
+            (u.cross(&v).abs() < EPSILON && u.dot(&v) > 0.0).then(|| line(p.start, q.end))
+        }
+        (FitSegment::Arc(p), FitSegment::Arc(q)) => {
+            let same = p.ccw == q.ccw
+                && p.center.distance_to(&q.center) <= tol
+                && (p.radius - q.radius).abs() <= tol
+                && p.sweep_angle() + q.sweep_angle() < 2.0 * PI - EPSILON;
+            same.then(|| FitSegment::Arc(Arc2D::new(p.center, p.radius, p.start_angle, q.end_angle, p.ccw)))
+        }
+        _ => None,
+    }
+}
+
+/// Parameter pairs where two segments cross
+fn crossings(a: &FitSegment, b: &FitSegment, tol: f64) -> Vec<(f64, f64)> {
+    let points = match (a, b) {
+        (FitSegment::Line(p), FitSegment::Line(q)) => {
+            let (d1, d2) = (p.end - p.start, q.end - q.start);
+            let denom = d1.cross(&d2);
+            if denom.abs() < EPSILON * d1.dot(&d1).sqrt() * d2.dot(&d2).sqrt() {
+                return Vec::new();
+            }
+            let t = (q.start - p.start).cross(&d2) / denom;
+            vec![p.start + d1 * t]
+        }
+        (FitSegment::Line(l), FitSegment::Arc(arc)) | (FitSegment::Arc(arc), FitSegment::Line(l)) => {
+            line_circle(l, arc.center, arc.radius, tol)
+        }
+        (FitSegment::Arc(p), FitSegment::Arc(q)) => circle_circle(p, q, tol),
+    };
+
+    let window = -EPSILON..=1.0 + EPSILON;
+    points
+        .into_iter()
+        .map(|point| (param(a, &point), param(b, &point)))
+        .filter(|(s, t)| window.contains(s) && window.contains(t))
+        .map(|(s, t)| (s.clamp(0.0, 1.0), t.clamp(0.0, 1.0)))
+        .collect()
+}
+
+/// Parameters where each segment meets an end of the other when they lie
+/// on the same line or circle
+fn overlaps(a: &FitSegment, b: &FitSegment, tol: f64) -> (Vec<f64>, Vec<f64>) {
+    let coincident = match (a, b) {
+        (FitSegment::Line(p), FitSegment::Line(q)) => {
+            let on = |point: &Point2D| {
+                let d = p.end - p.start;
+                (*point - p.start).cross(&d).abs() <= tol * d.dot(&d).sqrt()
+            };
+            on(&q.start) && on(&q.end)
+        }
+        (FitSegment::Arc(p), FitSegment::Arc(q)) => {
+            p.center.distance_to(&q.center) <= tol && (p.radius - q.radius).abs() <= tol
+        }
+        _ => false,
+    };
+    if !coincident {
+        return (Vec::new(), Vec::new());
+    }
+    let ends_on = |target: &FitSegment, other: &FitSegment| -> Vec<f64> {
+        [other.start(), other.end()]
+            .iter()
+            .map(|point| param(target, point))
+            .filter(|t| *t > 0.0 && *t < 1.0)
+            .collect()
+    };
+    (ends_on(a, b), ends_on(b, a))
+}
+
+fn line_circle(l: &LineSegment2D, center: Point2D, radius: f64, tol: f64) -> Vec<Point2D> {
+    let d = l.end - l.start;
+    let f = l.start - center;
+    let a = d.dot(&d);
+    let b = 2.0 * f.dot(&d);
+    let c = f.dot(&f) - radius * radius;
+    let mut disc = b * b - 4.0 * a * c;
+    if disc < 0.0 {
+        // Grazing contact within tolerance counts as a touch
+        if disc < -4.0 * a * tol * tol {
+            return Vec::new();
+        }
+        disc = 0.0;
+    }
+    let root = disc.sqrt();
+    [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+        .iter()
+        .map(|t| l.start + d * *t)
+        .collect()
+}
+
+fn circle_circle(p: &Arc2D, q: &Arc2D, tol: f64) -> Vec<Point2D> {
+    let between = q.center - p.center;
+    let d = between.dot(&between).sqrt();
+    if d < tol || d > p.radius + q.radius + tol || d < (p.radius - q.radius).abs() - tol {
+        return Vec::new();
+    }
+    let a = (p.radius * p.radius - q.radius * q.radius + d * d) / (2.0 * d);
+    let h = (p.radius * p.radius - a * a).max(0.0).sqrt();
+    let u = between * (1.0 / d);
+    let base = p.center + u * a;
+    let n = left_normal(u);
+    vec![base + n * h, base - n * h]
+}
+
+/// Position of `point` along a segment, 0 at the start and 1 at the end
+fn param(segment: &FitSegment, point: &Point2D) -> f64 {
+    match segment {
+        FitSegment::Line(l) => {
+            let d = l.end - l.start;
+            (*point - l.start).dot(&d) / d.dot(&d)
+        }
+        FitSegment::Arc(arc) => {
+            let sweep = arc.sweep_angle();
+            let angle = angle_of(*point - arc.center);
+            let mut turned = if arc.ccw {
+                angle - arc.start_angle
+            } else {
+                arc.start_angle - angle
+            }
+            .rem_euclid(2.0 * PI);
+            // Just short of the start reads as a small negative turn
+            if turned > sweep + (2.0 * PI - sweep) / 2.0 {
+                turned -= 2.0 * PI;
+            }
+            turned / sweep
+        }
+    }
+}
+
+fn arc_angle(arc: &Arc2D, t: f64) -> f64 {
+    let turn = arc.sweep_angle() * t;
+    if arc.ccw {
+        arc.start_angle + turn
+    } else {
+        arc.start_angle - turn
+    }
+}
+
+fn point_at(segment: &FitSegment, t: f64) -> Point2D {
+    match segment {
+        FitSegment::Line(l) => l.start.lerp(&l.end, t),
+        FitSegment::Arc(arc) => arc.point_at_angle(arc_angle(arc, t)),
+    }
+}
+
+fn sub_segment(segment: &FitSegment, from: f64, to: f64) -> FitSegment {
+    match segment {
+        FitSegment::Line(_) => line(point_at(segment, from), point_at(segment, to)),
+        FitSegment::Arc(arc) => FitSegment::Arc(Arc2D::new(
+            arc.center,
+            arc.radius,
+            arc_angle(arc, from),
+            arc_angle(arc, to),
+            arc.ccw,
+        )),
+    }
+}
+
+fn distance_to(segment: &FitSegment, point: &Point2D) -> f64 {
+    match segment {
+        FitSegment::Line(l) => l.distance_to_point(point),
+        FitSegment::Arc(arc) => {
+            let t = param(segment, point);
+            if (0.0..=1.0).contains(&t) {
+                (arc.center.distance_to(point) - arc.radius).abs()
+            } else {
+                point
+                    .distance_to(&segment.start())
+                    .min(point.distance_to(&segment.end()))
+            }
+        }
+    }
+}
+
+fn reverse(segment: &FitSegment) -> FitSegment {
+    match segment {
+        FitSegment::Line(l) => FitSegment::Line(l.reverse()),
+        FitSegment::Arc(arc) => FitSegment::Arc(arc.reverse()),
+    }
+}
+
+fn start_tangent(segment: &FitSegment) -> Point2D {
+    match segment {
+        FitSegment::Line(l) => (l.end - l.start).normalize(),
+        FitSegment::Arc(arc) => arc_tangent(arc, arc.start_angle),
+    }
+}
+
+fn end_tangent(segment: &FitSegment) -> Point2D {
+    match segment {
+        FitSegment::Line(_) => start_tangent(segment),
+        FitSegment::Arc(arc) => arc_tangent(arc, arc.end_angle),
+    }
+}
+
+fn arc_tangent(arc: &Arc2D, angle: f64) -> Point2D {
+    let (sin, cos) = angle.sin_cos();
+    if arc.ccw {
+        Point2D::new(-sin, cos)
+    } else {
+        Point2D::new(sin, -cos)
+    }
+}
+
+fn left_normal(tangent: Point2D) -> Point2D {
+    Point2D::new(-tangent.y, tangent.x)
+}
+
+fn angle_of(v: Point2D) -> f64 {
+    v.y.atan2(v.x)
+}
+
+fn line(start: Point2D, end: Point2D) -> FitSegment {
+    FitSegment::Line(LineSegment2D::new(start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon(points: &[(f64, f64)]) -> Polyline2D {
+        Polyline2D::new(points.iter().map(|&(x, y)| Point2D::new(x, y)).collect(), true)
+    }
+
+    fn corners(path: &ArcPolyline) -> Vec<(f64, f64)> {
+        let mut points: Vec<(f64, f64)> = path
+            .vertices
+            .iter()
+            .map(|v| ((v.point.x * 1e6).round() / 1e6, (v.point.y * 1e6).round() / 1e6))
+            .collect();
+        points.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        points
+    }
+
+    #[test]
+    fn test_offset_square_joins() {
+        let square = polygon(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]);
+
+        // Counterclockwise, so negative is outward
+        let out = offset_polyline(&square, -1.0, &OffsetOptions::default());
+        assert_eq!(out.len(), 1);
+        assert!(out[0].closed);
+        assert_eq!(
+            corners(&out[0]),
+            vec![(-1.0, -1.0), (-1.0, 11.0), (11.0, -1.0), (11.0, 11.0)]
+        );
+
+        // A miter limit below the corner's tip length bevels it
+        let bevel = OffsetOptions::default().with_miter_limit(1.0);
+        let out = offset_polyline(&square, -1.0, &bevel);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].vertices.len(), 8);
+
+        let round = OffsetOptions::default().with_join(JoinStyle::Round);
+        let out = offset_polyline(&square, -1.0, &round);
+        assert_eq!(out[0].arc_count(), 4);
+        assert!((out[0].length() - (40.0 + 2.0 * PI)).abs() < 1e-9);
+
+        let inward = offset_polyline(&square, 2.0, &round);
+        assert_eq!(corners(&inward[0]), vec![(2.0, 2.0), (2.0, 8.0), (8.0, 2.0), (8.0, 8.0)]);
+        assert!(offset_polyline(&square, 6.0, &round).is_empty());
+    }
+
+    #[test]
+    fn test_offset_trims_narrow_waist() {
+        // Two squares joined by a neck 1 unit tall
+        let dumbbell = polygon(&[
+            (0.0, 0.0),
+            (4.0, 0.0),
+            (4.0, 1.5),
+            (6.0, 1.5),
+            (6.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 4.0),
+            (6.0, 4.0),
+            (6.0, 2.5),
+            (4.0, 2.5),
+            (4.0, 4.0),
+            (0.0, 4.0),
+        ]);
+
+        let out = offset_polyline(&dumbbell, 0.75, &OffsetOptions::default());
+        assert_eq!(out.len(), 2);
+        let mut loops: Vec<_> = out.iter().map(corners).collect();
+        loops.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(
+            loops[0],
+            vec![(0.75, 0.75), (0.75, 3.25), (3.25, 0.75), (3.25, 3.25)]
+        );
+        assert_eq!(
+            loops[1],
+            vec![(6.75, 0.75), (6.75, 3.25), (9.25, 0.75), (9.25, 3.25)]
+        );
+
+        // A smaller offset keeps the neck
+        let out = offset_polyline(&dumbbell, 0.25, &OffsetOptions::default());
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].vertices.len(), 12);
+    }
+
+    #[test]
+    fn test_offset_arc_and_caps() {
+        let arc = Arc2D::new(Point2D::origin(), 5.0, 0.0, PI / 2.0, true);
+        let inner = offset_arc(&arc, 1.0, &OffsetOptions::default());
+        assert_eq!(inner.len(), 1);
+        assert!((inner[0].length() - 4.0 * PI / 2.0).abs() < 1e-9);
+        assert!(offset_arc(&arc, 6.0, &OffsetOptions::default()).is_empty());
+
+        let bar = Polyline2D::new(vec![Point2D::new(0.0, 0.0), Point2D::new(10.0, 0.0)], false);
+        let butt = offset_polyline(&bar, 1.0, &OffsetOptions::default().with_cap(CapStyle::Butt));
+        assert!(butt[0].closed);
+        assert_eq!(corners(&butt[0]), vec![(0.0, -1.0), (0.0, 1.0), (10.0, -1.0), (10.0, 1.0)]);
+
+        let square = OffsetOptions::default().with_cap(CapStyle::Square);
+        let out = offset_polyline(&bar, -1.0, &square);
+        assert_eq!(corners(&out[0]), vec![(-1.0, -1.0), (-1.0, 1.0), (11.0, -1.0), (11.0, 1.0)]);
+
+        let round = OffsetOptions::default().with_cap(CapStyle::Round);
+        let out = offset_polyline(&bar, 1.0, &round);
+        assert_eq!(out[0].arc_count(), 2);
+        assert!((out[0].length() - (20.0 + 2.0 * PI)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_offset_spline_keeps_distance() {
+        let spline = BSpline::uniform(
+            vec![
+                Point2D::new(0.0, 0.0),
+                Point2D::new(10.0, 20.0),
+                Point2D::new(30.0, -10.0),
+                Point2D::new(40.0, 10.0),
+            ],
+            3,
+        )
+        .unwrap();
+        let source = spline_to_arcs(&spline, 0.001);
+        // Round joins keep exactly to the distance across fitted kinks
+        let round = OffsetOptions::default().with_join(JoinStyle::Round);
+        let out = offset_spline(&spline, 2.0, 0.001, &round);
+        assert_eq!(out.len(), 1);
+
+        for segment in out[0].segments() {
+            let probe = point_at(&segment, 0.5);
+            let clearance = source
+                .segments()
+                .iter()
+                .map(|s| distance_to(s, &probe))
+                .fold(f64::INFINITY, f64::min);
+            assert!((clearance - 2.0).abs() < 1e-6, "{}", clearance);
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//! Point clouds from laser scans and surveys
+//!
+//! A [`PointCloud`] stores millions of points compactly: positions are
+//! `f32` offsets from a shared `f64` origin, so survey coordinates far from
+//! zero keep sub-millimetre precision at a third of the memory of full
+//! `f64` points. Colors and intensities are optional per-point channels.
+//!
+//! [`PointCloud::voxel_downsample`] thins a cloud to one point per grid
+//! cell and [`PointCloud::chunks`] splits it into spatially compact pieces
+//! for culling and drawing a point budget at a time.
+
+use crate::core::primitives::{BoundingBox3, Point3};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Default most points per chunk
+pub const CHUNK_POINTS: usize = 65_536;
+
+/// Scan point cloud
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PointCloud {
+    /// Position every stored offset is relative to
+    pub origin: [f64; 3],
+    /// Point positions relative to `origin`
+    pub positions: Vec<[f32; 3]>,
+    /// Per-point colors; empty if the scan has none
+    #[serde(default)]
+    pub colors: Vec<[u8; 3]>,
+    /// Per-point return intensities; empty if the scan has none
+    #[serde(default)]
+    pub intensities: Vec<u16>,
+}
+
+/// Spatially compact subset of a cloud's points
+#[derive(Debug, Clone)]
+pub struct CloudChunk {
+    /// Point indices, shuffled so that any prefix is an even sample
+    pub indices: Vec<u32>,
+    /// Bounds of the points in the chunk
+    pub bounds: BoundingBox3,
+}
+
+impl PointCloud {
+    /// Empty cloud whose points are stored relative to `origin`
+    pub fn new(origin: [f64; 3]) -> Self {
+        Self {
+            origin,
+            ..Self::default()
+        }
+    }
+
+    /// Number of points
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Whether the cloud has no points
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Whether points carry colors
+    pub fn has_colors(&self) -> bool {
+        !self.colors.is_empty()
+    }
+
+    /// Whether points carry intensities
+    pub fn has_intensities(&self) -> bool {
+        !self.intensities.is_empty()
+    }
+
+    /// Add a point
+    ///
+    /// A channel is kept once any point has it: earlier points are back-filled
+    /// with white or zero intensity, as are later points without it.
+    pub fn push(&mut self, position: [f64; 3], color: Option<[u8; 3]>, intensity: Option<u16>) {
+        let n = self.positions.len();
+        if color.is_some() || self.has_colors() {
+            self.colors.resize(n, [255, 255, 255]);
+            self.colors.push(color.unwrap_or([255, 255, 255]));
+        }
+        if intensity.is_some() || self.has_intensities() {
+            self.intensities.resize(n, 0);
+            self.intensities.push(intensity.unwrap_or(0));
+        }
+        self.positions.push([
+            (position[0] - self.origin[0]) as f32,
+            (position[1] - self.origin[1]) as f32,
+            (position[2] - self.origin[2]) as f32,
+        ]);
+    }
+
+    /// Absolute position of point `index`
+    pub fn point(&self, index: usize) -> Point3 {
+        let p = self.positions[index];
+        Point3::new(
+            self.origin[0] + p[0] as f64,
+            self.origin[1] + p[1] as f64,
+            self.origin[2] + p[2] as f64,
+        )
+    }
+
+    /// Absolute positions of all points
+    pub fn points(&self) -> impl Iterator<Item = Point3> + '_ {
+        (0..self.len()).map(|i| self.point(i))
+    }
+
+    /// Bounds of all points, `None` for an empty cloud
+    pub fn bounds(&self) -> Option<BoundingBox3> {
+        let mut points = self.points();
+        let first = points.next()?;
+        let mut bounds = BoundingBox3::new(first, first);
+        points.for_each(|p| bounds.expand_to_include(&p));
+        Some(bounds)
+    }
+
+    fn bounds_of(&self, indices: &[u32]) -> Option<BoundingBox3> {
+        let (&first, rest) = indices.split_first()?;
+        let mut bounds = BoundingBox3::new(self.point(first as usize), self.point(first as usize));
+        for &i in rest {
+            bounds.expand_to_include(&self.point(i as usize));
+        }
+        Some(bounds)
+    }
+
+    /// Move every point by `offset`
+    pub fn translate(&mut self, offset: [f64; 3]) {
+        for (o, d) in self.origin.iter_mut().zip(offset) {
+            *o += d;
+        }
+    }
+
+    /// One point per cubic voxel of edge `voxel_size`
+    ///
+    /// Each occupied voxel is replaced by the centroid of its points, with
+    /// colors and intensities averaged. Voxels come out in the order their
+    /// first point appears. A non-positive size returns a copy.
+    pub fn voxel_downsample(&self, voxel_size: f64) -> PointCloud {
+        if voxel_size <= 0.0 || !voxel_size.is_finite() {
+            return self.clone();
+        }
+
+        // Sums per voxel: position, color, intensity, count
+        let mut slots: HashMap<[i64; 3], usize> = HashMap::new();
+        let mut sums: Vec<([f64; 3], [u64; 3], u64, u64)> = Vec::new();
+        for (i, p) in self.positions.iter().enumerate() {
+            let key = p.map(|c| (c as f64 / voxel_size).floor() as i64);
+            let slot = *slots.entry(key).or_insert_with(|| {
+                sums.push(([0.0; 3], [0; 3], 0, 0));
+                sums.len() - 1
+            });
+            let sum = &mut sums[slot];
+            for (total, c) in sum.0.iter_mut().zip(p) {
+                *total += *c as f64;
+            }
+            if let Some(color) = self.colors.get(i) {
+                for (total, c) in sum.1.iter_mut().zip(color) {
+                    *total += *c as u64;
+                }
+            }
+            sum.2 += self.intensities.get(i).copied().unwrap_or(0) as u64;
+            sum.3 += 1;
+        }
+
+        let mut out = PointCloud::new(self.origin);
+        out.positions.reserve(sums.len());
+        for (position, color, intensity, count) in sums {
+            let n = count as f64;
+            out.positions.push(position.map(|c| (c / n) as f32));
+            if self.has_colors() {
+                out.colors.push(color.map(|c| ((c + count / 2) / count) as u8));
+            }
+            if self.has_intensities() {
+                out.intensities.push(((intensity + count / 2) / count) as u16);
+            }
+        }
+        out
+    }
+
+    /// Keep every `step`th point
+    pub fn subsample(&self, step: usize) -> PointCloud {
+        let step = step.max(1);
+        PointCloud {
+            origin: self.origin,
+            positions: self.positions.iter().step_by(step).copied().collect(),
+            colors: self.colors.iter().step_by(step).copied().collect(),
+            intensities: self.intensities.iter().step_by(step).copied().collect(),
+        }
+    }
+
+    /// Split into chunks of at most `max_points` points
+    ///
+    /// The cloud is halved at the median of its longest axis until each part
+    /// fits, so chunks are compact boxes suitable for frustum culling. Points
+    /// within a chunk are shuffled (deterministically), so drawing the first
+    /// `n` indices of a chunk shows an even sample of it.
+    pub fn chunks(&self, max_points: usize) -> Vec<CloudChunk> {
+        let max_points = max_points.max(1);
+        let mut chunks = Vec::new();
+        let mut stack = vec![(0..self.len() as u32).collect::<Vec<u32>>()];
+        while let Some(mut indices) = stack.pop() {
+            let Some(bounds) = self.bounds_of(&indices) else {
+                continue;
+            };
+            if indices.len() <= max_points {
+                shuffle(&mut indices, chunks.len() as u64);
+                chunks.push(CloudChunk { indices, bounds });
+                continue;
+            }
+
+            let extent = [bounds.width(), bounds.height(), bounds.depth()];
+            let axis = (0..3)
+                .max_by(|&a, &b| extent[a].total_cmp(&extent[b]))
+                .unwrap_or(0);
+            let mid = indices.len() / 2;
+            indices.select_nth_unstable_by(mid, |&a, &b| {
+                self.positions[a as usize][axis].total_cmp(&self.positions[b as usize][axis])
+            });
+            let upper = indices.split_off(mid);
+            stack.push(upper);
+            stack.push(indices);
+        }
+        chunks
+    }
+}
+
+/// Fisher-Yates shuffle driven by a fixed-seed xorshift generator
+fn shuffle(indices: &mut [u32], seed: u64) {
+    let mut state = 0x9E37_79B9_7F4A_7C15_u64 ^ seed.wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    for i in (1..indices.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        indices.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 10 x 10 x 10 grid of points 0.1 apart, far from the origin
+    fn grid() -> PointCloud {
+        let base = [500_000.0, 4_200_000.0, 100.0];
+        let mut cloud = PointCloud::new(base);
+        for i in 0..10 {
+            for j in 0..10 {
+                for k in 0..10 {
+                    let p = [
+                        base[0] + i as f64 * 0.1,
+                        base[1] + j as f64 * 0.1,
+                        base[2] + k as f64 * 0.1,
+                    ];
+                    let color = (i < 5).then_some([200, 0, 0]);
+                    cloud.push(p, color, Some(i * 100));
+                }
+            }
+        }
+        cloud
+    }
+
+    #[test]
+    fn test_push_and_voxel_downsample() {
+        let cloud = grid();
+        assert_eq!(cloud.len(), 1000);
+        assert_eq!(cloud.colors.len(), 1000);
+        // Points after the colored ones are filled with white
+        assert_eq!(cloud.colors[999], [255, 255, 255]);
+        let bounds = cloud.bounds().unwrap();
+        assert!((bounds.max.x - 500_000.9).abs() < 1e-4);
+        assert!((bounds.max.y - 4_200_000.9).abs() < 1e-4);
+
+        // 0.5 voxels take 5 x 5 x 5 points each
+        let thinned = cloud.voxel_downsample(0.5);
+        assert_eq!(thinned.len(), 8);
+        assert_eq!(thinned.colors.len(), 8);
+        let first = thinned.point(0);
+        assert!((first.x - 500_000.2).abs() < 1e-4);
+        assert!((first.z - 100.2).abs() < 1e-4);
+        assert_eq!(thinned.colors[0], [200, 0, 0]);
+        assert_eq!(thinned.intensities[0], 200);
+        assert_eq!(cloud.voxel_downsample(0.0), cloud);
+        assert_eq!(cloud.subsample(10).len(), 100);
+    }
+
+    #[test]
+    fn test_chunks_partition_the_cloud() {
+        let cloud = grid();
+        let chunks = cloud.chunks(300);
+        assert_eq!(chunks.len(), 4);
+
+        let mut seen: Vec<u32> = chunks.iter().flat_map(|c| c.indices.clone()).collect();
+        seen.sort_unstable();
+        assert_eq!(seen, (0..1000).collect::<Vec<_>>());
+        for chunk in &chunks {
+            assert!(chunk.indices.len() <= 300);
+            assert!(chunk.indices.iter().all(|&i| chunk.bounds.contains(&cloud.point(i as usize))));
+            // Halving along the longest axis keeps chunks compact
+            assert!(chunk.bounds.volume() < 0.25 + 1e-4);
+        }
+        let again: Vec<Vec<u32>> = cloud.chunks(300).into_iter().map(|c| c.indices).collect();
+        assert_eq!(again, chunks.iter().map(|c| c.indices.clone()).collect::<Vec<_>>());
+        assert!(PointCloud::default().chunks(10).is_empty());
+    }
+}
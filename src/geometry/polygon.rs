@@ -3,9 +3,12 @@
 //! Provides polygons with hole support, area/centroid calculation,
 //! point-in-polygon tests, convex hull algorithm, and offsetting.
 
+use crate::core::precision::{orient2d_sign, Tolerance};
 use crate::core::*;
+use crate::geometry::hull::convex_hull_2d;
 use crate::geometry::line::LineSegment2D;
 use crate::geometry::point::Point2D;
+use crate::geometry::tessellate::FillMesh;
 use nalgebra::Point2 as NPoint2;
 use serde::{Deserialize, Serialize};
 
@@ -120,6 +123,12 @@ impl Polygon2D {
 
     /// Check if the polygon is convex
     pub fn is_convex(&self) -> bool {
+        self.is_convex_within(&Tolerance::default())
+    }
+
+    /// Check if the polygon is convex, ignoring turns within `tolerance`
+    /// unless it asks for exact predicates
+    pub fn is_convex_within(&self, tolerance: &Tolerance) -> bool {
         if self.vertices.len() < 3 {
             return false;
         }
@@ -131,12 +140,8 @@ impl Polygon2D {
             let p2 = self.vertices[(i + 1) % self.vertices.len()];
             let p3 = self.vertices[(i + 2) % self.vertices.len()];
 
-            let v1 = Vector2::new(p2.x - p1.x, p2.y - p1.y);
-            let v2 = Vector2::new(p3.x - p2.x, p3.y - p2.y);
-            let cross = v1.x * v2.y - v1.y * v2.x;
-
-            if cross.abs() > EPSILON {
-                let current_sign = if cross > 0.0 { 1 } else { -1 };
+            let current_sign = orient2d_sign([p1.x, p1.y], [p2.x, p2.y], [p3.x, p3.y], tolerance);
+            if current_sign != 0 {
                 if sign == 0 {
                     sign = current_sign;
                 } else if sign != current_sign {
@@ -214,9 +219,9 @@ impl Polygon2D {
         BoundingBox2::from_points(&points)
     }
 
-    /// Compute convex hull using Graham scan algorithm
+    /// Compute the convex hull of the outer boundary
     pub fn convex_hull(&self) -> Polygon2D {
-        graham_scan(&self.vertices)
+        Polygon2D::new(convex_hull_2d(&self.vertices))
     }
 
     /// Offset the polygon (positive = outward, negative = inward)
@@ -271,9 +276,9 @@ impl Polygon2D {
         Polygon2D::new(offset_vertices)
     }
 
-    /// Triangulate the polygon using ear clipping algorithm
+    /// Triangulate the polygon, holes included, by ear clipping
     pub fn triangulate(&self) -> Vec<[Point2D; 3]> {
-        ear_clipping(&self.vertices)
+        FillMesh::from_polygon(self).triangles().collect()
     }
 
     /// Check if the polygon is simple (non-self-intersecting)
@@ -338,162 +343,6 @@ impl Polygon2D {
     }
 }
 
-/// Graham scan algorithm for convex hull
-fn graham_scan(points: &[Point2D]) -> Polygon2D {
-    if points.len() < 3 {
-        return Polygon2D::new(points.to_vec());
-    }
-
-    // Find the point with the lowest y-coordinate (and leftmost if tie)
-    let mut start_idx = 0;
-    for i in 1..points.len() {
-        if points[i].y < points[start_idx].y
-            || (points[i].y == points[start_idx].y && points[i].x < points[start_idx].x)
-        {
-            start_idx = i;
-        }
-    }
-
-    let start_point = points[start_idx];
-
-    // Sort points by polar angle with respect to start point
-    let mut sorted_points: Vec<Point2D> = points
-        .iter()
-        .enumerate()
-        .filter(|(i, _)| *i != start_idx)
-        .map(|(_, p)| *p)
-        .collect();
-
-    sorted_points.sort_by(|a, b| {
-        let angle_a = (a.y - start_point.y).atan2(a.x - start_point.x);
-        let angle_b = (b.y - start_point.y).atan2(b.x - start_point.x);
-        angle_a.partial_cmp(&angle_b).unwrap()
-    });
-
-    // Build convex hull
-    let mut hull = vec![start_point];
-    if !sorted_points.is_empty() {
-        hull.push(sorted_points[0]);
-    }
-
-    for point in sorted_points.iter().skip(1) {
-        while hull.len() >= 2 {
-            let p1 = hull[hull.len() - 2];
-            let p2 = hull[hull.len() - 1];
-
-            let v1 = Vector2::new(p2.x - p1.x, p2.y - p1.y);
-            let v2 = Vector2::new(point.x - p2.x, point.y - p2.y);
-            let cross = v1.x * v2.y - v1.y * v2.x;
-
-            if cross <= 0.0 {
-                hull.pop();
-            } else {
-                break;
-            }
-        }
-
-        hull.push(*point);
-    }
-
-    Polygon2D::new(hull)
-}
-
-/// Ear clipping triangulation algorithm
-fn ear_clipping(vertices: &[Point2D]) -> Vec<[Point2D; 3]> {
-    if vertices.len() < 3 {
-        return Vec::new();
-    }
-
-    let mut triangles = Vec::new();
-    let mut remaining: Vec<usize> = (0..vertices.len()).collect();
-
-    while remaining.len() > 3 {
-        let mut ear_found = false;
-
-        for i in 0..remaining.len() {
-            let prev_idx = if i == 0 {
-                remaining.len() - 1
-            } else {
-                i - 1
-            };
-            let next_idx = (i + 1) % remaining.len();
-
-            let p_prev = vertices[remaining[prev_idx]];
-            let p_curr = vertices[remaining[i]];
-            let p_next = vertices[remaining[next_idx]];
-
-            // Check if this is an ear
-            if is_ear(p_prev, p_curr, p_next, vertices, &remaining) {
-                triangles.push([p_prev, p_curr, p_next]);
-                remaining.remove(i);
-                ear_found = true;
-                break;
-            }
-        }
-
-        if !ear_found {
-            // Couldn't find an ear - polygon might be invalid
-            break;
-        }
-    }
-
-    // Add the last triangle
-    if remaining.len() == 3 {
-        triangles.push([
-            vertices[remaining[0]],
-            vertices[remaining[1]],
-            vertices[remaining[2]],
-        ]);
-    }
-
-    triangles
-}
-
-/// Check if three consecutive vertices form an ear
-fn is_ear(p1: Point2D, p2: Point2D, p3: Point2D, vertices: &[Point2D], remaining: &[usize]) -> bool {
-    // Check if the triangle is oriented correctly (CCW)
-    let v1 = Vector2::new(p2.x - p1.x, p2.y - p1.y);
-    let v2 = Vector2::new(p3.x - p2.x, p3.y - p2.y);
-    let cross = v1.x * v2.y - v1.y * v2.x;
-
-    if cross <= 0.0 {
-        return false; // Not a convex vertex
-    }
-
-    // Check if any other vertex is inside the triangle
-    for &idx in remaining {
-        let p = vertices[idx];
-        if p.approx_eq(&p1) || p.approx_eq(&p2) || p.approx_eq(&p3) {
-            continue;
-        }
-
-        if point_in_triangle(&p, &p1, &p2, &p3) {
-            return false;
-        }
-    }
-
-    true
-}
-
-/// Check if a point is inside a triangle
-fn point_in_triangle(p: &Point2D, a: &Point2D, b: &Point2D, c: &Point2D) -> bool {
-    let v0 = Vector2::new(c.x - a.x, c.y - a.y);
-    let v1 = Vector2::new(b.x - a.x, b.y - a.y);
-    let v2 = Vector2::new(p.x - a.x, p.y - a.y);
-
-    let dot00 = v0.dot(&v0);
-    let dot01 = v0.dot(&v1);
-    let dot02 = v0.dot(&v2);
-    let dot11 = v1.dot(&v1);
-    let dot12 = v1.dot(&v2);
-
-    let inv_denom = 1.0 / (dot00 * dot11 - dot01 * dot01);
-    let u = (dot11 * dot02 - dot01 * dot12) * inv_denom;
-    let v = (dot00 * dot12 - dot01 * dot02) * inv_denom;
-
-    u >= 0.0 && v >= 0.0 && (u + v) < 1.0
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
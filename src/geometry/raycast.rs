@@ -0,0 +1,876 @@
+//! Scene-wide ray casting
+//!
+//! A [`RayCaster`] holds the pickable 3D geometry of a scene - triangle
+//! meshes, solid primitives and tessellated parametric surfaces - under
+//! caller-chosen keys, with a [`SpatialIndex`] over their bounds. A cast
+//! walks the index in order of where the ray enters each box and stops as
+//! soon as the next box starts beyond the nearest hit found, so only the
+//! geometry the ray actually reaches is tested.
+//!
+//! Hits are exact rather than against bounds: meshes are tested triangle by
+//! triangle (through a per-mesh index of their faces), solids analytically,
+//! and surface hits on the tessellation are refined onto the true surface.
+//! Every hit reports the face it landed on and the ray parameter, which is
+//! the distance from the ray origin since rays are normalized.
+
+use crate::core::primitives::{BoundingBox3, Ray3};
+use crate::geometry::mesh::{TriangleFace, TriangleMesh, Vertex};
+use crate::geometry::solid::{Box3D, Cone3D, Cylinder3D, Sphere3D, Torus3D, Wedge3D};
+use crate::geometry::spatial::{ray_entry, SpatialIndex};
+use crate::geometry::surface::ParametricSurface;
+use nalgebra::{Matrix3, Point3, Vector3};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// Hits closer to the ray origin than this are ignored, so a ray cast from
+/// a surface doesn't hit that surface again
+const MIN_DISTANCE: f64 = 1e-9;
+
+/// Largest number of sphere-tracing steps against a torus
+const TRACE_STEPS: usize = 256;
+
+/// Largest number of Newton steps refining a surface hit
+const REFINE_STEPS: usize = 8;
+
+/// Where a ray meets a piece of geometry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit<K = ()> {
+    /// Key of the geometry that was hit
+    pub key: K,
+    /// Face that was hit; see [`RayTarget`] for the numbering
+    pub face: usize,
+    /// Ray parameter of the hit, the distance from the ray origin
+    pub t: f64,
+    /// Hit point
+    pub point: Point3<f64>,
+    /// Unit normal of the face at the hit, facing out of solids and along
+    /// the winding of mesh triangles
+    pub normal: Vector3<f64>,
+    /// Surface parameters of the hit, for tessellated surfaces
+    pub uv: Option<(f64, f64)>,
+}
+
+impl RayHit {
+    fn with_key<K>(self, key: K) -> RayHit<K> {
+        RayHit {
+            key,
+            face: self.face,
+            t: self.t,
+            point: self.point,
+            normal: self.normal,
+            uv: self.uv,
+        }
+    }
+}
+
+/// Triangle mesh with an index over its faces
+#[derive(Debug, Clone)]
+pub struct MeshTarget {
+    mesh: TriangleMesh,
+    faces: SpatialIndex<usize>,
+}
+
+impl MeshTarget {
+    /// Index the faces of `mesh`
+    pub fn new(mesh: TriangleMesh) -> Self {
+        let faces = SpatialIndex::from_items(mesh.faces.iter().enumerate().map(|(i, face)| {
+            let [a, b, c] = face.vertices.map(|v| mesh.vertices[v].position);
+            (i, points_bounds(&[a, b, c]))
+        }));
+        Self { mesh, faces }
+    }
+
+    pub fn mesh(&self) -> &TriangleMesh {
+        &self.mesh
+    }
+
+    fn bounds(&self) -> Option<BoundingBox3> {
+        self.faces.total_bounds()
+    }
+
+    fn intersect(&self, ray: &Ray3, max_t: f64) -> Option<RayHit> {
+        let mut best: Option<(RayHit, [f64; 3])> = None;
+        for (face, entry) in self.faces.query_ray(ray, max_t) {
+            if best.is_some_and(|(hit, _)| entry > hit.t) {
+                break;
+            }
+            let [a, b, c] = self.mesh.faces[face].vertices.map(|v| self.mesh.vertices[v].position);
+            let Some((t, u, v)) = triangle_hit(ray, &a, &b, &c) else {
+                continue;
+            };
+            if t > max_t || best.is_some_and(|(hit, _)| t >= hit.t) {
+                continue;
+            }
+            let normal = (b - a).cross(&(c - a)).try_normalize(0.0).unwrap_or_else(Vector3::z);
+            let hit = RayHit {
+                key: (),
+                face,
+                t,
+                point: ray.point_at(t),
+                normal,
+                uv: None,
+            };
+            best = Some((hit, [1.0 - u - v, u, v]));
+        }
+
+        // Carry texture coordinates over when the mesh has them
+        best.map(|(mut hit, weights)| {
+            let corners = self.mesh.faces[hit.face].vertices.map(|v| &self.mesh.vertices[v]);
+            hit.uv = interpolate_uv(&corners, &weights);
+            hit
+        })
+    }
+}
+
+/// Parametric surface with a tessellation to cast against
+#[derive(Clone)]
+pub struct SurfaceTarget {
+    surface: Arc<dyn ParametricSurface + Send + Sync>,
+    tessellation: MeshTarget,
+}
+
+impl SurfaceTarget {
+    /// Tessellate `surface` into a `resolution` x `resolution` grid of
+    /// quads, each split into two triangles
+    pub fn new(surface: Arc<dyn ParametricSurface + Send + Sync>, resolution: usize) -> Self {
+        let n = resolution.max(1);
+        let ((u0, u1), (v0, v1)) = surface.parameter_range();
+        let mut mesh = TriangleMesh::new();
+        for i in 0..=n {
+            for j in 0..=n {
+                let u = u0 + (u1 - u0) * i as f64 / n as f64;
+                let v = v0 + (v1 - v0) * j as f64 / n as f64;
+                mesh.add_vertex(Vertex {
+                    position: surface.evaluate(u, v),
+                    normal: None,
+                    uv: Some((u, v)),
+                });
+            }
+        }
+        let corner = |i: usize, j: usize| i * (n + 1) + j;
+        for i in 0..n {
+            for j in 0..n {
+                let quad = [corner(i, j), corner(i + 1, j), corner(i + 1, j + 1), corner(i, j + 1)];
+                mesh.faces.push(TriangleFace::new(quad[0], quad[1], quad[2]));
+                mesh.faces.push(TriangleFace::new(quad[0], quad[2], quad[3]));
+            }
+        }
+        Self {
+            surface,
+            tessellation: MeshTarget::new(mesh),
+        }
+    }
+
+    pub fn surface(&self) -> &(dyn ParametricSurface + Send + Sync) {
+        self.surface.as_ref()
+    }
+
+    pub fn tessellation(&self) -> &TriangleMesh {
+        self.tessellation.mesh()
+    }
+
+    fn intersect(&self, ray: &Ray3, max_t: f64) -> Option<RayHit> {
+        let mut hit = self.tessellation.intersect(ray, max_t)?;
+        let (u, v) = hit.uv?;
+
+        // The refined hit must stay near the facet it started from, or
+        // Newton has wandered off to another part of the surface
+        let mesh = self.tessellation.mesh();
+        let corners = mesh.faces[hit.face].vertices.map(|i| mesh.vertices[i].position);
+        let reach = (corners[1] - corners[0])
+            .norm()
+            .max((corners[2] - corners[1]).norm())
+            .max((corners[0] - corners[2]).norm());
+        if let Some((t, u, v)) = self.refine(ray, hit.t, u, v) {
+            let point = ray.point_at(t);
+            if t > MIN_DISTANCE && t <= max_t && (point - hit.point).norm() <= reach {
+                hit.t = t;
+                hit.point = point;
+                hit.uv = Some((u, v));
+                hit.normal = self.surface.normal(u, v).into_inner();
+            }
+        }
+        Some(hit)
+    }
+
+    /// Newton's method on surface(u, v) = origin + t direction
+    fn refine(&self, ray: &Ray3, t: f64, u: f64, v: f64) -> Option<(f64, f64, f64)> {
+        let ((u0, u1), (v0, v1)) = self.surface.parameter_range();
+        let tolerance = 1e-10 * (1.0 + t.abs());
+        let (mut t, mut u, mut v) = (t, u, v);
+        for _ in 0..REFINE_STEPS {
+            let residual = self.surface.evaluate(u, v) - ray.point_at(t);
+            if residual.norm() <= tolerance {
+                return Some((t, u, v));
+            }
+            let jacobian = Matrix3::from_columns(&[
+                self.surface.partial_u(u, v),
+                self.surface.partial_v(u, v),
+                -ray.direction,
+            ]);
+            let step = jacobian.lu().solve(&residual)?;
+            u = (u - step.x).clamp(u0, u1);
+            v = (v - step.y).clamp(v0, v1);
+            t -= step.z;
+        }
+        let residual = self.surface.evaluate(u, v) - ray.point_at(t);
+        (residual.norm() <= tolerance.sqrt()).then_some((t, u, v))
+    }
+}
+
+impl fmt::Debug for SurfaceTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SurfaceTarget")
+            .field("parameter_range", &self.surface.parameter_range())
+            .field("triangles", &self.tessellation.mesh().faces.len())
+            .finish()
+    }
+}
+
+/// Geometry a ray can be cast against
+///
+/// Faces are numbered per kind:
+///
+/// - `Mesh`: the triangle index
+/// - `Surface`: the triangle index in the tessellation
+/// - `Box`: 0 to 5 for the -x, +x, -y, +y, -z and +z sides in the box's frame
+/// - `Wedge`: 0 bottom, 1 back, 2 slope, 3 and 4 the triangular ends
+/// - `Sphere`, `Torus`: 0
+/// - `Cylinder`: 0 side, 1 base cap, 2 top cap
+/// - `Cone`: 0 side, 1 base cap
+#[derive(Debug, Clone)]
+pub enum RayTarget {
+    Mesh(MeshTarget),
+    Surface(SurfaceTarget),
+    Box(Box3D),
+    Wedge(Wedge3D),
+    Sphere(Sphere3D),
+    Cylinder(Cylinder3D),
+    Cone(Cone3D),
+    Torus(Torus3D),
+}
+
+impl RayTarget {
+    /// Triangle mesh, indexed by face
+    pub fn mesh(mesh: TriangleMesh) -> Self {
+        RayTarget::Mesh(MeshTarget::new(mesh))
+    }
+
+    /// Parametric surface, tessellated `resolution` quads to a side
+    pub fn surface(surface: Arc<dyn ParametricSurface + Send + Sync>, resolution: usize) -> Self {
+        RayTarget::Surface(SurfaceTarget::new(surface, resolution))
+    }
+
+    /// Bounds of the geometry; `None` for an empty mesh
+    pub fn bounds(&self) -> Option<BoundingBox3> {
+        match self {
+            RayTarget::Mesh(mesh) => mesh.bounds(),
+            RayTarget::Surface(surface) => surface.tessellation.bounds(),
+            RayTarget::Box(solid) => Some(points_bounds(&solid.vertices())),
+            RayTarget::Wedge(solid) => Some(points_bounds(&solid.vertices())),
+            RayTarget::Sphere(sphere) => {
+                let r = Vector3::repeat(sphere.radius);
+                Some(BoundingBox3::new(sphere.center - r, sphere.center + r))
+            }
+            RayTarget::Cylinder(cylinder) => {
+                let base = disk_bounds(&cylinder.base, &cylinder.axis, cylinder.radius);
+                let top = disk_bounds(&cylinder.top(), &cylinder.axis, cylinder.radius);
+                Some(points_bounds(&[base.min, base.max, top.min, top.max]))
+            }
+            RayTarget::Cone(cone) => {
+                let base = disk_bounds(&cone.base, &cone.axis, cone.radius);
+                Some(points_bounds(&[base.min, base.max, cone.apex()]))
+            }
+            RayTarget::Torus(torus) => {
+                let ring = disk_bounds(&torus.center, &torus.normal, torus.major_radius);
+                let tube = Vector3::repeat(torus.minor_radius);
+                Some(BoundingBox3::new(ring.min - tube, ring.max + tube))
+            }
+        }
+    }
+
+    /// Nearest hit within `max_t` of the ray origin
+    ///
+    /// A ray starting inside a solid hits the solid's boundary on the way
+    /// out. Mesh and surface triangles are hit from either side.
+    pub fn intersect(&self, ray: &Ray3, max_t: f64) -> Option<RayHit> {
+        let hit = match self {
+            RayTarget::Mesh(mesh) => return mesh.intersect(ray, max_t),
+            RayTarget::Surface(surface) => return surface.intersect(ray, max_t),
+            RayTarget::Box(solid) => {
+                // Sides as -x, +x, -y, +y, -z, +z over Box3D::vertices
+                const SIDES: [&[usize]; 6] = [
+                    &[0, 3, 7, 4],
+                    &[1, 2, 6, 5],
+                    &[0, 1, 5, 4],
+                    &[3, 2, 6, 7],
+                    &[0, 1, 2, 3],
+                    &[4, 5, 6, 7],
+                ];
+                convex_hit(ray, &solid.vertices(), &SIDES)
+            }
+            RayTarget::Wedge(solid) => {
+                // Bottom, back, slope and ends over Wedge3D::vertices
+                const SIDES: [&[usize]; 5] = [&[0, 1, 3, 2], &[0, 2, 5, 4], &[1, 4, 5, 3], &[0, 4, 1], &[2, 3, 5]];
+                convex_hit(ray, &solid.vertices(), &SIDES)
+            }
+            RayTarget::Sphere(sphere) => sphere_hit(ray, sphere),
+            RayTarget::Cylinder(cylinder) => cylinder_hit(ray, cylinder),
+            RayTarget::Cone(cone) => cone_hit(ray, cone),
+            RayTarget::Torus(torus) => torus_hit(ray, torus, self.bounds()?),
+        };
+        let (t, face, normal) = hit?;
+        (t <= max_t).then(|| RayHit {
+            key: (),
+            face,
+            t,
+            point: ray.point_at(t),
+            normal,
+            uv: None,
+        })
+    }
+}
+
+impl From<TriangleMesh> for RayTarget {
+    fn from(mesh: TriangleMesh) -> Self {
+        RayTarget::mesh(mesh)
+    }
+}
+
+impl From<Box3D> for RayTarget {
+    fn from(solid: Box3D) -> Self {
+        RayTarget::Box(solid)
+    }
+}
+
+impl From<Wedge3D> for RayTarget {
+    fn from(solid: Wedge3D) -> Self {
+        RayTarget::Wedge(solid)
+    }
+}
+
+impl From<Sphere3D> for RayTarget {
+    fn from(solid: Sphere3D) -> Self {
+        RayTarget::Sphere(solid)
+    }
+}
+
+impl From<Cylinder3D> for RayTarget {
+    fn from(solid: Cylinder3D) -> Self {
+        RayTarget::Cylinder(solid)
+    }
+}
+
+impl From<Cone3D> for RayTarget {
+    fn from(solid: Cone3D) -> Self {
+        RayTarget::Cone(solid)
+    }
+}
+
+impl From<Torus3D> for RayTarget {
+    fn from(solid: Torus3D) -> Self {
+        RayTarget::Torus(solid)
+    }
+}
+
+/// Pickable 3D geometry keyed by `K`, with a spatial index over its bounds
+#[derive(Debug, Clone)]
+pub struct RayCaster<K> {
+    targets: HashMap<K, RayTarget>,
+    index: SpatialIndex<K>,
+}
+
+impl<K> Default for RayCaster<K> {
+    fn default() -> Self {
+        Self {
+            targets: HashMap::new(),
+            index: SpatialIndex::default(),
+        }
+    }
+}
+
+impl<K: Copy + Eq + Hash> RayCaster<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.targets.contains_key(key)
+    }
+
+    pub fn get(&self, key: &K) -> Option<&RayTarget> {
+        self.targets.get(key)
+    }
+
+    /// The index over target bounds
+    pub fn index(&self) -> &SpatialIndex<K> {
+        &self.index
+    }
+
+    /// Add geometry, replacing any under the same key
+    pub fn insert(&mut self, key: K, target: impl Into<RayTarget>) {
+        let target = target.into();
+        match target.bounds() {
+            Some(bounds) => self.index.insert(key, bounds),
+            None => {
+                self.index.remove(&key);
+            }
+        }
+        self.targets.insert(key, target);
+    }
+
+    /// Remove geometry
+    pub fn remove(&mut self, key: &K) -> Option<RayTarget> {
+        self.index.remove(key);
+        self.targets.remove(key)
+    }
+
+    /// Nearest hit within `max_t` of the ray origin
+    pub fn cast(&self, ray: &Ray3, max_t: f64) -> Option<RayHit<K>> {
+        self.cast_by(ray, max_t, |_| true)
+    }
+
+    /// Nearest hit within `max_t` on geometry whose key `accept` allows
+    pub fn cast_by(&self, ray: &Ray3, max_t: f64, mut accept: impl FnMut(K) -> bool) -> Option<RayHit<K>> {
+        let mut best: Option<RayHit<K>> = None;
+        for (key, entry) in self.index.query_ray(ray, max_t) {
+            if best.is_some_and(|hit| entry > hit.t) {
+                break;
+            }
+            if !accept(key) {
+                continue;
+            }
+            let limit = best.map_or(max_t, |hit| hit.t);
+            if let Some(hit) = self.targets.get(&key).and_then(|target| target.intersect(ray, limit)) {
+                if best.is_none_or(|b| hit.t < b.t) {
+                    best = Some(hit.with_key(key));
+                }
+            }
+        }
+        best
+    }
+
+    /// Nearest hit on each piece of geometry within `max_t`, nearest first
+    pub fn cast_all(&self, ray: &Ray3, max_t: f64) -> Vec<RayHit<K>> {
+        self.cast_all_by(ray, max_t, |_| true)
+    }
+
+    /// Nearest hit on each piece of geometry whose key `accept` allows,
+    /// nearest first
+    pub fn cast_all_by(&self, ray: &Ray3, max_t: f64, mut accept: impl FnMut(K) -> bool) -> Vec<RayHit<K>> {
+        let mut hits: Vec<RayHit<K>> = self
+            .index
+            .query_ray(ray, max_t)
+            .into_iter()
+            .filter(|&(key, _)| accept(key))
+            .filter_map(|(key, _)| {
+                let hit = self.targets.get(&key)?.intersect(ray, max_t)?;
+                Some(hit.with_key(key))
+            })
+            .collect();
+        hits.sort_by(|a, b| a.t.total_cmp(&b.t));
+        hits
+    }
+}
+
+/// Möller-Trumbore ray-triangle test, from either side
+///
+/// Returns the ray parameter and the barycentric weights of `b` and `c`.
+fn triangle_hit(ray: &Ray3, a: &Point3<f64>, b: &Point3<f64>, c: &Point3<f64>) -> Option<(f64, f64, f64)> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = ray.direction.cross(&edge2);
+    let det = edge1.dot(&p);
+    if det.abs() < 1e-12 * edge1.norm() * edge2.norm() {
+        return None;
+    }
+    let inv = 1.0 / det;
+    let s = ray.origin - a;
+    let u = s.dot(&p) * inv;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(&edge1);
+    let v = ray.direction.dot(&q) * inv;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = edge2.dot(&q) * inv;
+    (t > MIN_DISTANCE).then_some((t, u, v))
+}
+
+fn interpolate_uv(corners: &[&Vertex; 3], weights: &[f64; 3]) -> Option<(f64, f64)> {
+    let mut uv = (0.0, 0.0);
+    for (vertex, w) in corners.iter().zip(weights) {
+        let (u, v) = vertex.uv?;
+        uv.0 += u * w;
+        uv.1 += v * w;
+    }
+    Some(uv)
+}
+
+/// Hit on a convex polyhedron given by its vertices and side loops
+///
+/// Clips the ray against the plane of every side: the last side entered is
+/// the one hit, or the first side left when the ray starts inside.
+fn convex_hit(ray: &Ray3, vertices: &[Point3<f64>], sides: &[&[usize]]) -> Option<(f64, usize, Vector3<f64>)> {
+    let centroid = Point3::from(vertices.iter().map(|p| p.coords).sum::<Vector3<f64>>() / vertices.len() as f64);
+    let mut enter = (f64::NEG_INFINITY, 0, Vector3::zeros());
+    let mut exit = (f64::INFINITY, 0, Vector3::zeros());
+    for (side, loop_) in sides.iter().enumerate() {
+        let origin = vertices[loop_[0]];
+        let mut normal = (vertices[loop_[1]] - origin)
+            .cross(&(vertices[loop_[2]] - origin))
+            .try_normalize(0.0)?;
+        if normal.dot(&(origin - centroid)) < 0.0 {
+            normal = -normal;
+        }
+        let distance = normal.dot(&(origin - ray.origin));
+        let rate = normal.dot(&ray.direction);
+        if rate.abs() < 1e-12 {
+            if distance < 0.0 {
+                return None;
+            }
+            continue;
+        }
+        let t = distance / rate;
+        if rate < 0.0 {
+            if t > enter.0 {
+                enter = (t, side, normal);
+            }
+        } else if t < exit.0 {
+            exit = (t, side, normal);
+        }
+        if enter.0 > exit.0 {
+            return None;
+        }
+    }
+    if enter.0 > MIN_DISTANCE {
+        Some(enter)
+    } else if exit.0 > MIN_DISTANCE && exit.0.is_finite() {
+        Some(exit)
+    } else {
+        None
+    }
+}
+
+/// Real roots of a t^2 + b t + c, ascending
+fn quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < 1e-12 {
+        return if b.abs() < 1e-12 { Vec::new() } else { vec![-c / b] };
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return Vec::new();
+    }
+    // Avoid cancellation in the smaller root
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let mut roots = if q == 0.0 { vec![0.0] } else { vec![q / a, c / q] };
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+fn nearest(candidates: impl IntoIterator<Item = (f64, usize, Vector3<f64>)>) -> Option<(f64, usize, Vector3<f64>)> {
+    candidates
+        .into_iter()
+        .filter(|(t, _, _)| *t > MIN_DISTANCE)
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+fn sphere_hit(ray: &Ray3, sphere: &Sphere3D) -> Option<(f64, usize, Vector3<f64>)> {
+    let offset = ray.origin - sphere.center;
+    let roots = quadratic(
+        1.0,
+        2.0 * offset.dot(&ray.direction),
+        offset.norm_squared() - sphere.radius.powi(2),
+    );
+    nearest(
+        roots
+            .into_iter()
+            .map(|t| (t, 0, (ray.point_at(t) - sphere.center) / sphere.radius)),
+    )
+}
+
+/// Ray in a frame along `axis` from `base`: the part of origin and
+/// direction across the axis, and their heights along it
+struct AxialRay {
+    across_origin: Vector3<f64>,
+    across_direction: Vector3<f64>,
+    height: f64,
+    rise: f64,
+}
+
+impl AxialRay {
+    fn new(ray: &Ray3, base: &Point3<f64>, axis: &Vector3<f64>) -> Self {
+        let offset = ray.origin - base;
+        let height = offset.dot(axis);
+        let rise = ray.direction.dot(axis);
+        Self {
+            across_origin: offset - axis * height,
+            across_direction: ray.direction - axis * rise,
+            height,
+            rise,
+        }
+    }
+
+    fn height_at(&self, t: f64) -> f64 {
+        self.height + self.rise * t
+    }
+
+    fn across_at(&self, t: f64) -> Vector3<f64> {
+        self.across_origin + self.across_direction * t
+    }
+
+    /// Hit on the disk of `radius` at `height`, facing `normal`
+    fn cap(&self, height: f64, radius: f64, face: usize, normal: Vector3<f64>) -> Option<(f64, usize, Vector3<f64>)> {
+        if self.rise.abs() < 1e-12 {
+            return None;
+        }
+        let t = (height - self.height) / self.rise;
+        (self.across_at(t).norm_squared() <= radius * radius).then_some((t, face, normal))
+    }
+}
+
+fn cylinder_hit(ray: &Ray3, cylinder: &Cylinder3D) -> Option<(f64, usize, Vector3<f64>)> {
+    let axis = cylinder.axis.into_inner();
+    let local = AxialRay::new(ray, &cylinder.base, &axis);
+    let side = quadratic(
+        local.across_direction.norm_squared(),
+        2.0 * local.across_origin.dot(&local.across_direction),
+        local.across_origin.norm_squared() - cylinder.radius.powi(2),
+    )
+    .into_iter()
+    .filter(|&t| (0.0..=cylinder.height).contains(&local.height_at(t)))
+    .map(|t| (t, 0, local.across_at(t) / cylinder.radius));
+    nearest(side.chain(local.cap(0.0, cylinder.radius, 1, -axis)).chain(local.cap(
+        cylinder.height,
+        cylinder.radius,
+        2,
+        axis,
+    )))
+}
+
+fn cone_hit(ray: &Ray3, cone: &Cone3D) -> Option<(f64, usize, Vector3<f64>)> {
+    let axis = cone.axis.into_inner();
+    let local = AxialRay::new(ray, &cone.base, &axis);
+    // |across| = k (height - h) on the side, with h measured from the base
+    let k = cone.radius / cone.height;
+    let k2 = k * k;
+    let q = cone.height - local.height;
+    let side = quadratic(
+        local.across_direction.norm_squared() - k2 * local.rise * local.rise,
+        2.0 * (local.across_origin.dot(&local.across_direction) + k2 * q * local.rise),
+        local.across_origin.norm_squared() - k2 * q * q,
+    )
+    .into_iter()
+    .filter(|&t| (0.0..=cone.height).contains(&local.height_at(t)))
+    .filter_map(|t| {
+        let radial = local.across_at(t).try_normalize(0.0)?;
+        Some((t, 0, (radial + axis * k).normalize()))
+    });
+    nearest(side.chain(local.cap(0.0, cone.radius, 1, -axis)))
+}
+
+/// Sphere-trace the torus distance field, which is exact, from where the ray
+/// enters its bounds
+fn torus_hit(ray: &Ray3, torus: &Torus3D, bounds: BoundingBox3) -> Option<(f64, usize, Vector3<f64>)> {
+    let axis = torus.normal.into_inner();
+    let tolerance = 1e-10 * (torus.major_radius + torus.minor_radius);
+    let distance = |t: f64| {
+        let offset = ray.point_at(t) - torus.center;
+        let height = offset.dot(&axis);
+        let ring = (offset - axis * height).norm() - torus.major_radius;
+        (ring * ring + height * height).sqrt() - torus.minor_radius
+    };
+
+    // Steps of |distance| never overshoot, from outside or in
+    let mut t = ray_entry(ray, &bounds)?.max(MIN_DISTANCE);
+    // Leaving the bounds ends the search
+    let reach = t + (bounds.max - bounds.min).norm();
+    for _ in 0..TRACE_STEPS {
+        let d = distance(t);
+        if d.abs() <= tolerance && t > MIN_DISTANCE {
+            let offset = ray.point_at(t) - torus.center;
+            let height = offset.dot(&axis);
+            let radial = (offset - axis * height)
+                .try_normalize(0.0)
+                .unwrap_or_else(|| offset.normalize());
+            let tube = torus.center + radial * torus.major_radius;
+            return Some((t, 0, (ray.point_at(t) - tube).normalize()));
+        }
+        t += d.abs().max(tolerance);
+        if t > reach {
+            return None;
+        }
+    }
+    None
+}
+
+fn points_bounds(points: &[Point3<f64>]) -> BoundingBox3 {
+    let mut min = points[0];
+    let mut max = points[0];
+    for p in &points[1..] {
+        min = min.inf(p);
+        max = max.sup(p);
+    }
+    BoundingBox3::new(min, max)
+}
+
+/// Exact bounds of a disk: along each axis it reaches r sqrt(1 - n_i^2)
+fn disk_bounds(center: &Point3<f64>, normal: &Vector3<f64>, radius: f64) -> BoundingBox3 {
+    let reach = normal.map(|n| radius * (1.0 - n * n).max(0.0).sqrt());
+    BoundingBox3::new(center - reach, center + reach)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Matrix4;
+
+    fn ray(origin: [f64; 3], direction: [f64; 3]) -> Ray3 {
+        Ray3::new(Point3::from(origin), Vector3::from(direction))
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn test_solid_hits_report_face_and_distance() {
+        let down = ray([0.2, 0.1, 10.0], [0.0, 0.0, -1.0]);
+
+        let cube = RayTarget::from(Box3D::cube(Point3::origin(), 2.0));
+        let hit = cube.intersect(&down, 100.0).unwrap();
+        assert!(close(hit.t, 9.0));
+        assert_eq!(hit.face, 5);
+        assert!(close(hit.normal.z, 1.0));
+
+        // Rotated a quarter turn about x, the box's +y side faces up
+        let turned = Box3D::new(Point3::origin(), 2.0, 4.0, 6.0).with_rotation(Matrix4::from_axis_angle(
+            &Vector3::x_axis(),
+            std::f64::consts::FRAC_PI_2,
+        ));
+        let hit = RayTarget::from(turned).intersect(&down, 100.0).unwrap();
+        assert!(close(hit.t, 8.0));
+        assert_eq!(hit.face, 3);
+
+        let sphere = RayTarget::from(Sphere3D::new(Point3::new(0.0, 0.0, 1.0), 1.0));
+        let hit = sphere
+            .intersect(&ray([0.0, 0.0, 10.0], [0.0, 0.0, -1.0]), 100.0)
+            .unwrap();
+        assert!(close(hit.t, 8.0));
+        // From the inside the ray hits the far wall
+        let hit = sphere.intersect(&ray([0.0, 0.0, 1.0], [1.0, 0.0, 0.0]), 100.0).unwrap();
+        assert!(close(hit.t, 1.0));
+
+        let cylinder = RayTarget::from(Cylinder3D::z_aligned(Point3::origin(), 1.0, 3.0));
+        assert_eq!(cylinder.intersect(&down, 100.0).unwrap().face, 2);
+        let side = cylinder
+            .intersect(&ray([-5.0, 0.0, 1.0], [1.0, 0.0, 0.0]), 100.0)
+            .unwrap();
+        assert!(close(side.t, 4.0));
+        assert_eq!(side.face, 0);
+
+        let cone = RayTarget::from(Cone3D::z_aligned(Point3::origin(), 1.0, 2.0));
+        let hit = cone.intersect(&ray([-5.0, 0.0, 1.0], [1.0, 0.0, 0.0]), 100.0).unwrap();
+        assert!(close(hit.t, 4.5));
+        assert!(close(hit.normal.dot(&Vector3::new(-2.0, 0.0, 1.0).normalize()), 1.0));
+        assert_eq!(
+            cone.intersect(&ray([0.2, 0.0, -3.0], [0.0, 0.0, 1.0]), 100.0)
+                .unwrap()
+                .face,
+            1
+        );
+
+        // Through the hole of a torus, then onto its tube
+        let torus = RayTarget::from(Torus3D::xy_plane(Point3::origin(), 3.0, 1.0));
+        assert!(torus
+            .intersect(&ray([0.0, 0.0, 10.0], [0.0, 0.0, -1.0]), 100.0)
+            .is_none());
+        let hit = torus
+            .intersect(&ray([3.0, 0.0, 10.0], [0.0, 0.0, -1.0]), 100.0)
+            .unwrap();
+        assert!(close(hit.t, 9.0));
+        let hit = torus
+            .intersect(&ray([-10.0, 0.0, 0.0], [1.0, 0.0, 0.0]), 100.0)
+            .unwrap();
+        assert!(close(hit.t, 6.0));
+        assert!(close(hit.normal.x, -1.0));
+
+        let wedge = RayTarget::from(Wedge3D::new(Point3::origin(), 2.0, 2.0, 1.0));
+        let hit = wedge
+            .intersect(&ray([1.5, 10.0, 0.5], [0.0, -1.0, 0.0]), 100.0)
+            .unwrap();
+        assert!(close(hit.t, 9.5));
+        assert_eq!(hit.face, 2);
+        assert!(wedge.intersect(&down, 5.0).is_none());
+    }
+
+    #[test]
+    fn test_caster_returns_nearest_entity_and_face() {
+        // Two stacked triangles and a sphere behind them
+        let mut mesh = TriangleMesh::new();
+        for z in [1.0, 2.0] {
+            let a = mesh.add_vertex(Vertex::new(Point3::new(-1.0, -1.0, z)));
+            let b = mesh.add_vertex(Vertex::new(Point3::new(2.0, -1.0, z)));
+            let c = mesh.add_vertex(Vertex::new(Point3::new(-1.0, 2.0, z)));
+            mesh.add_face(TriangleFace::new(a, b, c));
+        }
+        let mut caster = RayCaster::new();
+        caster.insert("mesh", mesh);
+        caster.insert("ball", Sphere3D::new(Point3::new(0.0, 0.0, -3.0), 1.0));
+        caster.insert("far", Sphere3D::new(Point3::new(50.0, 0.0, 0.0), 1.0));
+
+        let down = ray([0.0, 0.0, 10.0], [0.0, 0.0, -1.0]);
+        let hit = caster.cast(&down, 100.0).unwrap();
+        assert_eq!((hit.key, hit.face), ("mesh", 1));
+        assert!(close(hit.t, 8.0));
+
+        let hit = caster.cast_by(&down, 100.0, |key| key != "mesh").unwrap();
+        assert_eq!(hit.key, "ball");
+        assert!(close(hit.point.z, -2.0));
+
+        let all = caster.cast_all(&down, 100.0);
+        assert_eq!(all.iter().map(|h| h.key).collect::<Vec<_>>(), vec!["mesh", "ball"]);
+        assert!(caster.cast(&down, 5.0).is_none());
+
+        caster.remove(&"mesh");
+        assert_eq!(caster.cast(&down, 100.0).unwrap().key, "ball");
+    }
+
+    #[test]
+    fn test_surface_hit_is_refined_onto_the_surface() {
+        // A coarse tessellation of a sphere-like cap: z = 4 - x^2 - y^2
+        struct Cap;
+        impl ParametricSurface for Cap {
+            fn evaluate(&self, u: f64, v: f64) -> Point3<f64> {
+                Point3::new(u, v, 4.0 - u * u - v * v)
+            }
+            fn normal(&self, u: f64, v: f64) -> nalgebra::Unit<Vector3<f64>> {
+                nalgebra::Unit::new_normalize(Vector3::new(2.0 * u, 2.0 * v, 1.0))
+            }
+            fn parameter_range(&self) -> ((f64, f64), (f64, f64)) {
+                ((-1.0, 1.0), (-1.0, 1.0))
+            }
+        }
+
+        let target = RayTarget::surface(Arc::new(Cap), 4);
+        let hit = target
+            .intersect(&ray([0.3, 0.2, 10.0], [0.0, 0.0, -1.0]), 100.0)
+            .unwrap();
+        let (u, v) = hit.uv.unwrap();
+        assert!(close(u, 0.3) && close(v, 0.2));
+        assert!(close(hit.point.z, 4.0 - 0.09 - 0.04));
+        assert!(close(hit.normal.dot(&Vector3::new(0.6, 0.4, 1.0).normalize()), 1.0));
+    }
+}
@@ -0,0 +1,546 @@
+//! Isotropic remeshing
+//!
+//! [`Remesher`] rebuilds a triangle mesh with edges close to a target
+//! length and well-shaped, near-equilateral triangles, following Botsch and
+//! Kobbelt. Each iteration:
+//!
+//! 1. Splits edges longer than 4/3 of the target at their midpoint
+//! 2. Collapses edges shorter than 4/5 of the target, unless that would
+//!    create a long edge, turn a face over or pinch the surface
+//! 3. Flips edges where that brings vertex valences closer to 6 (4 on
+//!    open edges)
+//! 4. Moves each vertex toward the centroid of its neighbours within its
+//!    tangent plane
+//!
+//! Vertices on open edges, and on creases sharper than the feature angle,
+//! are locked, so outlines and the edges of CAD solids stay where they are
+//! while the faces between them are evened out. Decimate first with
+//! [`Decimator`](super::decimate::Decimator) when the aim is fewer triangles.
+
+use crate::geometry::mesh::{TriangleFace, TriangleMesh, Vertex};
+use nalgebra::{Point3, Vector3};
+use std::collections::{HashMap, HashSet};
+
+/// Remeshing settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct Remesher {
+    /// Edge length to aim for
+    pub target_edge_length: f64,
+    /// Split, collapse, flip and relax rounds
+    pub iterations: usize,
+    /// Dihedral angle (radians) above which an edge is a crease to keep
+    pub feature_angle: f64,
+}
+
+impl Default for Remesher {
+    fn default() -> Self {
+        Self {
+            target_edge_length: 1.0,
+            iterations: 5,
+            feature_angle: 45f64.to_radians(),
+        }
+    }
+}
+
+/// Mean length of a mesh's edges, a starting point for the target length
+pub fn mean_edge_length(mesh: &TriangleMesh) -> f64 {
+    let edges = edge_faces(&faces_of(mesh));
+    if edges.is_empty() {
+        return 0.0;
+    }
+    let total: f64 = edges
+        .keys()
+        .map(|&(a, b)| nalgebra::distance(&mesh.vertices[a].position, &mesh.vertices[b].position))
+        .sum();
+    total / edges.len() as f64
+}
+
+fn faces_of(mesh: &TriangleMesh) -> Vec<[usize; 3]> {
+    mesh.faces.iter().map(|f| f.vertices).collect()
+}
+
+/// Faces around each undirected edge, lower vertex first
+fn edge_faces(faces: &[[usize; 3]]) -> HashMap<(usize, usize), Vec<usize>> {
+    let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (i, f) in faces.iter().enumerate() {
+        for k in 0..3 {
+            let (a, b) = (f[k], f[(k + 1) % 3]);
+            edges.entry((a.min(b), a.max(b))).or_default().push(i);
+        }
+    }
+    edges
+}
+
+/// Edges sorted for a deterministic pass order
+fn sorted_edges(edges: &HashMap<(usize, usize), Vec<usize>>) -> Vec<(usize, usize)> {
+    let mut keys: Vec<(usize, usize)> = edges.keys().copied().collect();
+    keys.sort_unstable();
+    keys
+}
+
+/// Working mesh: positions and faces only
+struct Surface {
+    positions: Vec<Point3<f64>>,
+    faces: Vec<[usize; 3]>,
+    cos_feature: f64,
+}
+
+impl Surface {
+    fn normal(&self, face: [usize; 3]) -> Vector3<f64> {
+        let [a, b, c] = face.map(|v| self.positions[v]);
+        (b - a).cross(&(c - a))
+    }
+
+    fn length(&self, (a, b): (usize, usize)) -> f64 {
+        nalgebra::distance(&self.positions[a], &self.positions[b])
+    }
+
+    /// Whether an edge must be kept: open, shared by more than two faces,
+    /// or a crease
+    fn is_feature(&self, faces: &[usize]) -> bool {
+        match *faces {
+            [f, g] => {
+                let (n, m) = (self.normal(self.faces[f]), self.normal(self.faces[g]));
+                match (n.try_normalize(0.0), m.try_normalize(0.0)) {
+                    (Some(n), Some(m)) => n.dot(&m) < self.cos_feature,
+                    _ => false,
+                }
+            }
+            _ => true,
+        }
+    }
+
+    /// Vertices on a feature edge
+    fn locked(&self, edges: &HashMap<(usize, usize), Vec<usize>>) -> Vec<bool> {
+        let mut locked = vec![false; self.positions.len()];
+        for (&(a, b), faces) in edges {
+            if self.is_feature(faces) {
+                locked[a] = true;
+                locked[b] = true;
+            }
+        }
+        locked
+    }
+
+    fn vertex_faces(&self) -> Vec<Vec<usize>> {
+        let mut vertex_faces = vec![Vec::new(); self.positions.len()];
+        for (i, face) in self.faces.iter().enumerate() {
+            for &v in face {
+                vertex_faces[v].push(i);
+            }
+        }
+        vertex_faces
+    }
+
+    fn split_long_edges(&mut self, high: f64) -> usize {
+        let mut total = 0;
+        loop {
+            let edges = edge_faces(&self.faces);
+            let mut long: Vec<((usize, usize), f64)> = sorted_edges(&edges)
+                .into_iter()
+                .map(|e| (e, self.length(e)))
+                .filter(|&(_, length)| length > high)
+                .collect();
+            long.sort_by(|x, y| y.1.total_cmp(&x.1));
+
+            let mut touched = vec![false; self.faces.len()];
+            let mut split = 0;
+            for ((a, b), _) in long {
+                let faces = &edges[&(a, b)];
+                if faces.iter().any(|&f| touched[f]) {
+                    continue;
+                }
+                let m = self.positions.len();
+                self.positions
+                    .push(nalgebra::center(&self.positions[a], &self.positions[b]));
+                for &f in faces {
+                    touched[f] = true;
+                    let face = self.faces[f];
+                    let Some(k) = (0..3).find(|&k| {
+                        let (p, q) = (face[k], face[(k + 1) % 3]);
+                        (p, q) == (a, b) || (p, q) == (b, a)
+                    }) else {
+                        continue;
+                    };
+                    let (p, q, r) = (face[k], face[(k + 1) % 3], face[(k + 2) % 3]);
+                    self.faces[f] = [p, m, r];
+                    self.faces.push([m, q, r]);
+                }
+                split += 1;
+            }
+            total += split;
+            if split == 0 {
+                return total;
+            }
+        }
+    }
+
+    fn collapse_short_edges(&mut self, low: f64, high: f64) -> usize {
+        let mut total = 0;
+        loop {
+            let edges = edge_faces(&self.faces);
+            let locked = self.locked(&edges);
+            let vertex_faces = self.vertex_faces();
+            let mut short: Vec<((usize, usize), f64)> = sorted_edges(&edges)
+                .into_iter()
+                .map(|e| (e, self.length(e)))
+                .filter(|&(_, length)| length < low)
+                .collect();
+            short.sort_by(|x, y| x.1.total_cmp(&y.1));
+
+            let mut touched = vec![false; self.faces.len()];
+            let mut dead = vec![false; self.faces.len()];
+            let mut collapsed = 0;
+            for ((a, b), _) in short {
+                let shared = &edges[&(a, b)];
+                if locked[a] || locked[b] || shared.len() != 2 {
+                    continue;
+                }
+                let around: Vec<usize> = vertex_faces[a]
+                    .iter()
+                    .chain(&vertex_faces[b])
+                    .copied()
+                    .collect();
+                if around.iter().any(|&f| touched[f]) {
+                    continue;
+                }
+
+                // Link condition: only the two apexes are neighbours of both
+                let neighbors = |v: usize| -> HashSet<usize> {
+                    vertex_faces[v]
+                        .iter()
+                        .flat_map(|&f| self.faces[f])
+                        .filter(|&w| w != a && w != b)
+                        .collect()
+                };
+                let (na, nb) = (neighbors(a), neighbors(b));
+                if na.intersection(&nb).count() != 2 {
+                    continue;
+                }
+
+                let mid = nalgebra::center(&self.positions[a], &self.positions[b]);
+                if na
+                    .iter()
+                    .chain(&nb)
+                    .any(|&w| nalgebra::distance(&mid, &self.positions[w]) > high)
+                {
+                    continue;
+                }
+                let flips = around.iter().filter(|f| !shared.contains(f)).any(|&f| {
+                    let face = self.faces[f];
+                    let before = self.normal(face);
+                    let [p, q, r] = face.map(|v| {
+                        if v == a || v == b {
+                            mid
+                        } else {
+                            self.positions[v]
+                        }
+                    });
+                    let after = (q - p).cross(&(r - p));
+                    before.dot(&after) <= 0.0
+                });
+                if flips {
+                    continue;
+                }
+
+                self.positions[a] = mid;
+                for &f in &around {
+                    touched[f] = true;
+                    if shared.contains(&f) {
+                        dead[f] = true;
+                    } else {
+                        for v in &mut self.faces[f] {
+                            if *v == b {
+                                *v = a;
+                            }
+                        }
+                    }
+                }
+                collapsed += 1;
+            }
+
+            let mut index = 0;
+            self.faces.retain(|_| {
+                index += 1;
+                !dead[index - 1]
+            });
+            total += collapsed;
+            if collapsed == 0 {
+                return total;
+            }
+        }
+    }
+
+    fn flip_edges(&mut self) -> usize {
+        let edges = edge_faces(&self.faces);
+        let locked = self.locked(&edges);
+        let mut valence = vec![0i64; self.positions.len()];
+        for &(a, b) in edges.keys() {
+            valence[a] += 1;
+            valence[b] += 1;
+        }
+        // Ideal valence: 6 inside, 4 on locked (open or crease) vertices
+        let target = |v: usize| if locked[v] { 4 } else { 6 };
+
+        let mut touched = vec![false; self.faces.len()];
+        let mut created = HashSet::new();
+        let mut flipped = 0;
+        for (a, b) in sorted_edges(&edges) {
+            let faces = &edges[&(a, b)];
+            if self.is_feature(faces) || faces.iter().any(|&f| touched[f]) {
+                continue;
+            }
+            // f1 runs a -> b, f2 runs b -> a
+            let runs = |f: usize, p: usize, q: usize| {
+                let face = self.faces[f];
+                (0..3)
+                    .find(|&k| face[k] == p && face[(k + 1) % 3] == q)
+                    .map(|k| face[(k + 2) % 3])
+            };
+            let (f1, f2) = (faces[0], faces[1]);
+            let (f1, f2, c, d) = match (
+                runs(f1, a, b),
+                runs(f2, b, a),
+                runs(f2, a, b),
+                runs(f1, b, a),
+            ) {
+                (Some(c), Some(d), _, _) => (f1, f2, c, d),
+                (_, _, Some(c), Some(d)) => (f2, f1, c, d),
+                _ => continue,
+            };
+            if c == d || valence[a] <= 3 || valence[b] <= 3 {
+                continue;
+            }
+            let diagonal = (c.min(d), c.max(d));
+            if edges.contains_key(&diagonal) || created.contains(&diagonal) {
+                continue;
+            }
+
+            let deviation = |values: [(usize, i64); 4]| -> i64 {
+                values.iter().map(|&(v, n)| (n - target(v)).abs()).sum()
+            };
+            let before = deviation([
+                (a, valence[a]),
+                (b, valence[b]),
+                (c, valence[c]),
+                (d, valence[d]),
+            ]);
+            let after = deviation([
+                (a, valence[a] - 1),
+                (b, valence[b] - 1),
+                (c, valence[c] + 1),
+                (d, valence[d] + 1),
+            ]);
+            if after >= before {
+                continue;
+            }
+            let reference = self.normal(self.faces[f1]) + self.normal(self.faces[f2]);
+            let (g1, g2) = ([c, a, d], [d, b, c]);
+            if self.normal(g1).dot(&reference) <= 0.0 || self.normal(g2).dot(&reference) <= 0.0 {
+                continue;
+            }
+
+            self.faces[f1] = g1;
+            self.faces[f2] = g2;
+            touched[f1] = true;
+            touched[f2] = true;
+            created.insert(diagonal);
+            valence[a] -= 1;
+            valence[b] -= 1;
+            valence[c] += 1;
+            valence[d] += 1;
+            flipped += 1;
+        }
+        flipped
+    }
+
+    /// Move unlocked vertices toward their neighbours' centroid, within
+    /// their tangent plane
+    fn relax(&mut self, amount: f64) {
+        let edges = edge_faces(&self.faces);
+        let locked = self.locked(&edges);
+        let mut sums = vec![(Vector3::zeros(), 0usize); self.positions.len()];
+        for &(a, b) in edges.keys() {
+            sums[a].0 += self.positions[b].coords;
+            sums[a].1 += 1;
+            sums[b].0 += self.positions[a].coords;
+            sums[b].1 += 1;
+        }
+        let mut normals = vec![Vector3::zeros(); self.positions.len()];
+        for &face in &self.faces {
+            let n = self.normal(face);
+            for v in face {
+                normals[v] += n;
+            }
+        }
+
+        for (v, position) in self.positions.iter_mut().enumerate() {
+            let (sum, count) = sums[v];
+            if locked[v] || count == 0 {
+                continue;
+            }
+            let Some(n) = normals[v].try_normalize(0.0) else {
+                continue;
+            };
+            let offset = sum / count as f64 - position.coords;
+            *position += (offset - n * n.dot(&offset)) * amount;
+        }
+    }
+
+    fn into_mesh(self) -> TriangleMesh {
+        let mut remap = vec![usize::MAX; self.positions.len()];
+        let mut mesh = TriangleMesh::new();
+        for face in &self.faces {
+            let corners = face.map(|v| {
+                if remap[v] == usize::MAX {
+                    remap[v] = mesh.add_vertex(Vertex::new(self.positions[v]));
+                }
+                remap[v]
+            });
+            mesh.faces.push(TriangleFace { vertices: corners });
+        }
+        mesh
+    }
+}
+
+impl Remesher {
+    /// Remesh toward edges of `target_edge_length`
+    pub fn new(target_edge_length: f64) -> Self {
+        Self {
+            target_edge_length,
+            ..Self::default()
+        }
+    }
+
+    /// Remeshed copy of `mesh`; vertex normals and texture coordinates are
+    /// not carried over
+    pub fn remesh(&self, mesh: &TriangleMesh) -> TriangleMesh {
+        let length = self.target_edge_length;
+        if !(length > 0.0 && length.is_finite()) {
+            return mesh.clone();
+        }
+        let mut surface = Surface {
+            positions: mesh.vertices.iter().map(|v| v.position).collect(),
+            faces: faces_of(mesh),
+            cos_feature: self.feature_angle.cos(),
+        };
+        let (low, high) = (length * 4.0 / 5.0, length * 4.0 / 3.0);
+        for _ in 0..self.iterations {
+            surface.split_long_edges(high);
+            surface.collapse_short_edges(low, high);
+            surface.flip_edges();
+            surface.relax(0.5);
+        }
+        surface.into_mesh()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::repair::MeshRepair;
+
+    /// Closed unit cube, two triangles per side, wound outward
+    fn cube() -> TriangleMesh {
+        let mut mesh = TriangleMesh::new();
+        for i in 0..8 {
+            mesh.add_vertex(Vertex::new(Point3::new(
+                (i & 1) as f64,
+                ((i >> 1) & 1) as f64,
+                ((i >> 2) & 1) as f64,
+            )));
+        }
+        for [a, b, c, d] in [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ] {
+            mesh.add_face(TriangleFace::new(a, b, c));
+            mesh.add_face(TriangleFace::new(a, c, d));
+        }
+        mesh
+    }
+
+    fn volume(mesh: &TriangleMesh) -> f64 {
+        mesh.faces
+            .iter()
+            .map(|f| {
+                let [a, b, c] = f.vertices.map(|v| mesh.vertices[v].position.coords);
+                a.dot(&b.cross(&c)) / 6.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_remesh_cube_keeps_shape() {
+        let original = cube();
+        let remeshed = Remesher::new(0.25).remesh(&original);
+
+        assert!(remeshed.faces.len() > 100, "{}", remeshed.faces.len());
+        assert!(MeshRepair::new().analyze(&remeshed).is_watertight());
+        // Creases are locked and faces stay flat, so the solid is unchanged
+        assert!((volume(&remeshed) - 1.0).abs() < 1e-9);
+        for v in &remeshed.vertices {
+            let p = v.position;
+            assert!([p.x, p.y, p.z]
+                .iter()
+                .all(|c| (-1e-9..=1.0 + 1e-9).contains(c)));
+        }
+
+        let mean = mean_edge_length(&remeshed);
+        assert!((mean - 0.25).abs() < 0.1, "mean edge length {}", mean);
+        // Edges end up within the split and collapse limits
+        let edges = edge_faces(&faces_of(&remeshed));
+        let longest = edges
+            .keys()
+            .map(|&(a, b)| {
+                nalgebra::distance(
+                    &remeshed.vertices[a].position,
+                    &remeshed.vertices[b].position,
+                )
+            })
+            .fold(0.0, f64::max);
+        assert!(longest <= 0.25 * 4.0 / 3.0 + 1e-9, "longest {}", longest);
+    }
+
+    #[test]
+    fn test_remesh_coarsens_and_keeps_outline() {
+        // Fine 20 x 20 grid over a 4 x 4 square
+        let n = 20;
+        let mut mesh = TriangleMesh::new();
+        for j in 0..=n {
+            for i in 0..=n {
+                mesh.add_vertex(Vertex::new(Point3::new(
+                    i as f64 * 0.2,
+                    j as f64 * 0.2,
+                    0.0,
+                )));
+            }
+        }
+        let at = |i: usize, j: usize| j * (n + 1) + i;
+        for j in 0..n {
+            for i in 0..n {
+                mesh.add_face(TriangleFace::new(at(i, j), at(i + 1, j), at(i + 1, j + 1)));
+                mesh.add_face(TriangleFace::new(at(i, j), at(i + 1, j + 1), at(i, j + 1)));
+            }
+        }
+
+        let remeshed = Remesher::new(0.6).remesh(&mesh);
+        assert!(remeshed.faces.len() < mesh.faces.len());
+        let area: f64 = remeshed
+            .faces
+            .iter()
+            .map(|f| {
+                let [a, b, c] = f.vertices.map(|v| remeshed.vertices[v].position);
+                (b - a).cross(&(c - a)).z / 2.0
+            })
+            .sum();
+        assert!((area - 16.0).abs() < 1e-9);
+        assert!(remeshed.vertices.iter().all(|v| v.position.z == 0.0));
+        assert_eq!(MeshRepair::new().analyze(&remeshed).holes, 1);
+        assert_eq!(Remesher::new(0.0).remesh(&mesh), mesh);
+    }
+}
@@ -0,0 +1,702 @@
+//! Mesh repair for 3D printing
+//!
+//! Meshes imported from STL or OBJ are often triangle soups with
+//! coincident vertices, collapsed and doubled faces, fins where three or
+//! more faces meet at an edge, inconsistent winding and holes. A printer's
+//! slicer needs a closed, manifold, outward-facing surface.
+//!
+//! [`MeshRepair::analyze`] reports these defects without touching the mesh;
+//! [`MeshRepair::repair`] fixes them in order and returns a
+//! [`RepairReport`] of what it changed:
+//!
+//! 1. Weld vertices closer than the tolerance
+//! 2. Drop faces that collapse onto an edge or point, and duplicate faces
+//! 3. Keep two faces per edge, preferring a consistently wound pair
+//! 4. Split vertices where separate fans of faces touch at a point
+//! 5. Flip faces to agree with their neighbours
+//! 6. Fill holes with triangulated patches
+//! 7. Turn closed shells inside out if their volume is negative
+
+use crate::geometry::mesh::{TriangleFace, TriangleMesh, Vertex};
+use crate::geometry::point::Point2D;
+use crate::geometry::tessellate::tessellate;
+use nalgebra::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Mesh repair settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshRepair {
+    /// Vertices closer than this are merged
+    pub weld_tolerance: f64,
+    /// Fill holes bounded by at most this many edges
+    pub max_hole_edges: usize,
+    /// Whether to fill holes at all
+    pub fill_holes: bool,
+    /// Whether to turn inside-out closed shells so normals point outward
+    pub orient_outward: bool,
+}
+
+impl Default for MeshRepair {
+    fn default() -> Self {
+        Self {
+            weld_tolerance: 1e-6,
+            max_hole_edges: 10_000,
+            fill_holes: true,
+            orient_outward: true,
+        }
+    }
+}
+
+/// Defects found in a mesh
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeshDiagnostics {
+    /// Vertex count
+    pub vertices: usize,
+    /// Face count
+    pub faces: usize,
+    /// Vertices within the weld tolerance of an earlier one
+    pub duplicate_vertices: usize,
+    /// Faces using a vertex more than once
+    pub degenerate_faces: usize,
+    /// Faces over the same three vertices as an earlier face
+    pub duplicate_faces: usize,
+    /// Edges shared by more than two faces
+    pub non_manifold_edges: usize,
+    /// Edges with both faces wound the same way
+    pub inconsistent_edges: usize,
+    /// Edges with a single face
+    pub boundary_edges: usize,
+    /// Closed loops of boundary edges
+    pub holes: usize,
+}
+
+impl MeshDiagnostics {
+    /// Whether the mesh is closed, manifold and consistently wound
+    pub fn is_watertight(&self) -> bool {
+        self.faces > 0
+            && self.degenerate_faces == 0
+            && self.duplicate_faces == 0
+            && self.non_manifold_edges == 0
+            && self.inconsistent_edges == 0
+            && self.boundary_edges == 0
+    }
+}
+
+/// Changes made by [`MeshRepair::repair`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepairReport {
+    /// Defects before repair
+    pub before: MeshDiagnostics,
+    /// Defects remaining after repair
+    pub after: MeshDiagnostics,
+    /// Vertices merged into a coincident one
+    pub merged_vertices: usize,
+    /// Faces dropped for using a vertex more than once
+    pub removed_degenerate_faces: usize,
+    /// Faces dropped as duplicates
+    pub removed_duplicate_faces: usize,
+    /// Faces dropped from edges shared by more than two faces
+    pub removed_non_manifold_faces: usize,
+    /// Vertices duplicated to separate fans touching at a point
+    pub split_vertices: usize,
+    /// Faces flipped to agree with their neighbours
+    pub flipped_faces: usize,
+    /// Closed shells turned outward
+    pub inverted_shells: usize,
+    /// Holes closed
+    pub filled_holes: usize,
+    /// Faces added to close holes
+    pub added_faces: usize,
+    /// Holes left open for having too many edges
+    pub unfilled_holes: usize,
+    /// Vertices no longer used by any face
+    pub removed_unused_vertices: usize,
+}
+
+impl RepairReport {
+    /// Whether the mesh was changed
+    pub fn changed(&self) -> bool {
+        !self.changes().is_empty()
+    }
+
+    /// One line per kind of change made
+    pub fn changes(&self) -> Vec<String> {
+        [
+            (self.merged_vertices, "merged duplicate vertices"),
+            (self.removed_degenerate_faces, "removed degenerate faces"),
+            (self.removed_duplicate_faces, "removed duplicate faces"),
+            (self.removed_non_manifold_faces, "removed faces from non-manifold edges"),
+            (self.split_vertices, "split non-manifold vertices"),
+            (self.flipped_faces, "flipped inconsistently wound faces"),
+            (self.inverted_shells, "turned inside-out shells outward"),
+            (self.filled_holes, "filled holes"),
+            (self.added_faces, "added faces to fill holes"),
+            (self.removed_unused_vertices, "removed unused vertices"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, what)| format!("{}: {}", what, count))
+        .collect()
+    }
+}
+
+impl MeshRepair {
+    /// Repair with default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Find defects without changing the mesh
+    ///
+    /// Face and edge defects are counted after welding, as [`repair`]
+    /// sees them.
+    ///
+    /// [`repair`]: MeshRepair::repair
+    pub fn analyze(&self, mesh: &TriangleMesh) -> MeshDiagnostics {
+        let mut welded = mesh.clone();
+        welded.merge_vertices(self.weld_tolerance);
+        let mut diagnostics = MeshDiagnostics {
+            vertices: mesh.vertices.len(),
+            faces: mesh.faces.len(),
+            duplicate_vertices: mesh.vertices.len() - welded.vertices.len(),
+            ..MeshDiagnostics::default()
+        };
+
+        let mut seen = HashSet::new();
+        for face in &welded.faces {
+            if is_degenerate(face) {
+                diagnostics.degenerate_faces += 1;
+            } else if !seen.insert(face_key(face)) {
+                diagnostics.duplicate_faces += 1;
+            }
+        }
+
+        let faces: Vec<&TriangleFace> = welded.faces.iter().filter(|f| !is_degenerate(f)).collect();
+        let mut edges: HashMap<(usize, usize), Vec<bool>> = HashMap::new();
+        for face in &faces {
+            for (a, b) in face.edges() {
+                edges.entry((a.min(b), a.max(b))).or_default().push(a < b);
+            }
+        }
+        for directions in edges.values() {
+            match directions.as_slice() {
+                [_] => diagnostics.boundary_edges += 1,
+                [a, b] if a == b => diagnostics.inconsistent_edges += 1,
+                [_, _] => {}
+                _ => diagnostics.non_manifold_edges += 1,
+            }
+        }
+        diagnostics.holes = boundary_loops(faces.iter().copied()).len();
+        diagnostics
+    }
+
+    /// Fix the mesh's defects in place
+    pub fn repair(&self, mesh: &mut TriangleMesh) -> RepairReport {
+        let mut report = RepairReport {
+            before: self.analyze(mesh),
+            ..RepairReport::default()
+        };
+
+        let count = mesh.vertices.len();
+        mesh.merge_vertices(self.weld_tolerance);
+        report.merged_vertices = count - mesh.vertices.len();
+
+        let count = mesh.faces.len();
+        mesh.faces.retain(|f| !is_degenerate(f));
+        report.removed_degenerate_faces = count - mesh.faces.len();
+
+        let count = mesh.faces.len();
+        let mut seen = HashSet::new();
+        mesh.faces.retain(|f| seen.insert(face_key(f)));
+        report.removed_duplicate_faces = count - mesh.faces.len();
+
+        report.removed_non_manifold_faces = remove_non_manifold_faces(mesh);
+        report.split_vertices = split_non_manifold_vertices(mesh);
+        report.flipped_faces = orient_consistently(mesh);
+
+        if self.fill_holes {
+            for hole in boundary_loops(mesh.faces.iter()) {
+                if hole.len() > self.max_hole_edges {
+                    report.unfilled_holes += 1;
+                    continue;
+                }
+                report.added_faces += fill_hole(mesh, &hole);
+                report.filled_holes += 1;
+            }
+        } else {
+            report.unfilled_holes = boundary_loops(mesh.faces.iter()).len();
+        }
+
+        if self.orient_outward {
+            report.inverted_shells = orient_outward(mesh);
+        }
+        report.removed_unused_vertices = remove_unused_vertices(mesh);
+        if mesh.vertices.iter().any(|v| v.normal.is_some()) {
+            mesh.compute_vertex_normals();
+        }
+
+        report.after = self.analyze(mesh);
+        report
+    }
+}
+
+fn is_degenerate(face: &TriangleFace) -> bool {
+    let [a, b, c] = face.vertices;
+    a == b || b == c || c == a
+}
+
+fn face_key(face: &TriangleFace) -> [usize; 3] {
+    let mut key = face.vertices;
+    key.sort_unstable();
+    key
+}
+
+/// Faces around each undirected edge, in face order
+fn edge_faces(faces: &[TriangleFace]) -> HashMap<(usize, usize), Vec<usize>> {
+    let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (i, face) in faces.iter().enumerate() {
+        for (a, b) in face.edges() {
+            edges.entry((a.min(b), a.max(b))).or_default().push(i);
+        }
+    }
+    edges
+}
+
+/// Whether `face` runs along the edge from `a` to `b`
+fn has_directed_edge(face: &TriangleFace, a: usize, b: usize) -> bool {
+    face.edges().contains(&(a, b))
+}
+
+/// Keep two faces on each edge shared by more: the first, and the first
+/// wound opposite to it if any. Returns the number of faces removed.
+fn remove_non_manifold_faces(mesh: &mut TriangleMesh) -> usize {
+    let mut edges: Vec<((usize, usize), Vec<usize>)> = edge_faces(&mesh.faces)
+        .into_iter()
+        .filter(|(_, faces)| faces.len() > 2)
+        .collect();
+    edges.sort_unstable();
+
+    let mut removed = vec![false; mesh.faces.len()];
+    for ((a, b), faces) in edges {
+        let faces: Vec<usize> = faces.into_iter().filter(|&f| !removed[f]).collect();
+        if faces.len() <= 2 {
+            continue;
+        }
+        let first = faces[0];
+        let forward = has_directed_edge(&mesh.faces[first], a, b);
+        let partner = faces[1..]
+            .iter()
+            .copied()
+            .find(|&f| has_directed_edge(&mesh.faces[f], a, b) != forward)
+            .unwrap_or(faces[1]);
+        for &f in &faces {
+            if f != first && f != partner {
+                removed[f] = true;
+            }
+        }
+    }
+
+    let mut index = 0;
+    mesh.faces.retain(|_| {
+        index += 1;
+        !removed[index - 1]
+    });
+    removed.iter().filter(|&&r| r).count()
+}
+
+/// Give each fan of faces around a vertex its own copy of the vertex when
+/// several fans touch only at that point. Returns the number of copies made.
+fn split_non_manifold_vertices(mesh: &mut TriangleMesh) -> usize {
+    let mut vertex_faces: Vec<Vec<usize>> = vec![Vec::new(); mesh.vertices.len()];
+    for (i, face) in mesh.faces.iter().enumerate() {
+        for &v in &face.vertices {
+            vertex_faces[v].push(i);
+        }
+    }
+
+    let mut copies = 0;
+    for (v, faces) in vertex_faces.iter().enumerate() {
+        if faces.len() < 2 {
+            continue;
+        }
+        // Faces around v are in one fan if they share another vertex
+        let mut fan: Vec<usize> = (0..faces.len()).collect();
+        let mut by_neighbor: HashMap<usize, usize> = HashMap::new();
+        for (k, &f) in faces.iter().enumerate() {
+            for &w in &mesh.faces[f].vertices {
+                if w == v {
+                    continue;
+                }
+                if let Some(&other) = by_neighbor.get(&w) {
+                    let (x, y) = (root(&mut fan, k), root(&mut fan, other));
+                    fan[x] = y;
+                } else {
+                    by_neighbor.insert(w, k);
+                }
+            }
+        }
+
+        let first_root = root(&mut fan, 0);
+        let mut copy_of: HashMap<usize, usize> = HashMap::new();
+        for (k, &f) in faces.iter().enumerate() {
+            let r = root(&mut fan, k);
+            if r == first_root {
+                continue;
+            }
+            let copy = *copy_of.entry(r).or_insert_with(|| {
+                mesh.vertices.push(mesh.vertices[v]);
+                copies += 1;
+                mesh.vertices.len() - 1
+            });
+            for corner in &mut mesh.faces[f].vertices {
+                if *corner == v {
+                    *corner = copy;
+                }
+            }
+        }
+    }
+    copies
+}
+
+/// Union-find root with path halving
+fn root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Shells of faces connected across edges, as a shell index per face
+fn shells(faces: &[TriangleFace]) -> (Vec<usize>, usize) {
+    let edges = edge_faces(faces);
+    let mut shell = vec![usize::MAX; faces.len()];
+    let mut count = 0;
+    for start in 0..faces.len() {
+        if shell[start] != usize::MAX {
+            continue;
+        }
+        shell[start] = count;
+        let mut queue = VecDeque::from([start]);
+        while let Some(f) = queue.pop_front() {
+            for (a, b) in faces[f].edges() {
+                for &g in &edges[&(a.min(b), a.max(b))] {
+                    if shell[g] == usize::MAX {
+                        shell[g] = count;
+                        queue.push_back(g);
+                    }
+                }
+            }
+        }
+        count += 1;
+    }
+    (shell, count)
+}
+
+/// Flip faces so each agrees in winding with the face it was reached from,
+/// growing outward from the first face of each shell. Returns the number
+/// of faces flipped.
+fn orient_consistently(mesh: &mut TriangleMesh) -> usize {
+    let edges = edge_faces(&mesh.faces);
+    let mut visited = vec![false; mesh.faces.len()];
+    let mut flipped = 0;
+    for start in 0..mesh.faces.len() {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut queue = VecDeque::from([start]);
+        while let Some(f) = queue.pop_front() {
+            for (a, b) in mesh.faces[f].edges() {
+                for &g in &edges[&(a.min(b), a.max(b))] {
+                    if visited[g] {
+                        continue;
+                    }
+                    visited[g] = true;
+                    // A neighbour running the same way along the edge is flipped
+                    if has_directed_edge(&mesh.faces[g], a, b) {
+                        mesh.faces[g] = mesh.faces[g].reverse();
+                        flipped += 1;
+                    }
+                    queue.push_back(g);
+                }
+            }
+        }
+    }
+    flipped
+}
+
+/// Loops of boundary edges, each in the winding a patch filling it needs
+fn boundary_loops<'a>(faces: impl Iterator<Item = &'a TriangleFace>) -> Vec<Vec<usize>> {
+    let mut directed: HashMap<(usize, usize), usize> = HashMap::new();
+    for face in faces {
+        for (a, b) in face.edges() {
+            *directed.entry((a, b)).or_default() += 1;
+        }
+    }
+    let mut undirected: HashMap<(usize, usize), usize> = HashMap::new();
+    for (&(a, b), &n) in &directed {
+        *undirected.entry((a.min(b), a.max(b))).or_default() += n;
+    }
+
+    // A boundary edge a -> b is crossed b -> a by the patch
+    let mut next: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut boundary: Vec<(usize, usize)> = directed
+        .keys()
+        .copied()
+        .filter(|&(a, b)| undirected[&(a.min(b), a.max(b))] == 1)
+        .collect();
+    boundary.sort_unstable();
+    for &(a, b) in &boundary {
+        next.entry(b).or_default().push(a);
+    }
+
+    let mut loops = Vec::new();
+    for &(a, b) in &boundary {
+        let Some(targets) = next.get_mut(&b) else {
+            continue;
+        };
+        let Some(position) = targets.iter().position(|&t| t == a) else {
+            continue;
+        };
+        targets.swap_remove(position);
+
+        let mut hole = vec![b];
+        let mut current = a;
+        while current != b {
+            hole.push(current);
+            match next.get_mut(&current).and_then(|t| t.pop()) {
+                Some(following) => current = following,
+                None => break,
+            }
+        }
+        if current == b && hole.len() >= 3 {
+            loops.push(hole);
+        }
+    }
+    loops
+}
+
+/// Close a hole with a triangulated patch. Returns the faces added.
+fn fill_hole(mesh: &mut TriangleMesh, hole: &[usize]) -> usize {
+    if let [a, b, c] = *hole {
+        mesh.faces.push(TriangleFace::new(a, b, c));
+        return 1;
+    }
+
+    let points: Vec<Point3<f64>> = hole.iter().map(|&v| mesh.vertices[v].position).collect();
+    // Newell normal of the loop, in the patch's winding
+    let mut normal = Vector3::zeros();
+    for (i, p) in points.iter().enumerate() {
+        let q = points[(i + 1) % points.len()];
+        normal += Vector3::new(
+            (p.y - q.y) * (p.z + q.z),
+            (p.z - q.z) * (p.x + q.x),
+            (p.x - q.x) * (p.y + q.y),
+        );
+    }
+
+    let triangles = match normal.try_normalize(f64::EPSILON) {
+        Some(normal) => {
+            // Right-handed basis in the loop's plane, so the loop is
+            // counter-clockwise and ear clipping keeps its winding
+            let helper = if normal.x.abs() < 0.9 { Vector3::x() } else { Vector3::y() };
+            let u = normal.cross(&helper).normalize();
+            let v = normal.cross(&u);
+            let flat: Vec<Point2D> = points
+                .iter()
+                .map(|p| Point2D::new(p.coords.dot(&u), p.coords.dot(&v)))
+                .collect();
+            tessellate(&flat, &[]).indices
+        }
+        None => Vec::new(),
+    };
+
+    if triangles.len() == 3 * (hole.len() - 2) {
+        for t in triangles.chunks_exact(3) {
+            mesh.faces.push(TriangleFace::new(
+                hole[t[0] as usize],
+                hole[t[1] as usize],
+                hole[t[2] as usize],
+            ));
+        }
+        return hole.len() - 2;
+    }
+
+    // Projection folds the loop over itself: fan from its centroid instead
+    let centroid = points.iter().fold(Vector3::zeros(), |sum, p| sum + p.coords) / points.len() as f64;
+    let center = mesh.add_vertex(Vertex::new(Point3::from(centroid)));
+    for (i, &v) in hole.iter().enumerate() {
+        mesh.faces.push(TriangleFace::new(center, v, hole[(i + 1) % hole.len()]));
+    }
+    hole.len()
+}
+
+/// Reverse every closed shell with negative volume. Returns the number of
+/// shells reversed.
+fn orient_outward(mesh: &mut TriangleMesh) -> usize {
+    let (shell, count) = shells(&mesh.faces);
+    let edges = edge_faces(&mesh.faces);
+    let mut closed = vec![true; count];
+    for faces in edges.values() {
+        if faces.len() != 2 {
+            closed[shell[faces[0]]] = false;
+        }
+    }
+
+    let mut volume = vec![0.0; count];
+    for (face, &s) in mesh.faces.iter().zip(&shell) {
+        let [a, b, c] = face.vertices.map(|v| mesh.vertices[v].position.coords);
+        volume[s] += a.dot(&b.cross(&c)) / 6.0;
+    }
+
+    let inverted: Vec<bool> = (0..count).map(|s| closed[s] && volume[s] < 0.0).collect();
+    for (face, &s) in mesh.faces.iter_mut().zip(&shell) {
+        if inverted[s] {
+            *face = face.reverse();
+        }
+    }
+    inverted.iter().filter(|&&i| i).count()
+}
+
+/// Drop vertices no face uses. Returns the number dropped.
+fn remove_unused_vertices(mesh: &mut TriangleMesh) -> usize {
+    let mut remap = vec![usize::MAX; mesh.vertices.len()];
+    for face in &mesh.faces {
+        for &v in &face.vertices {
+            remap[v] = 0;
+        }
+    }
+    let mut vertices = Vec::with_capacity(mesh.vertices.len());
+    for (old, slot) in remap.iter_mut().enumerate() {
+        if *slot == 0 {
+            *slot = vertices.len();
+            vertices.push(mesh.vertices[old]);
+        }
+    }
+    for face in &mut mesh.faces {
+        for v in &mut face.vertices {
+            *v = remap[*v];
+        }
+    }
+    let removed = mesh.vertices.len() - vertices.len();
+    mesh.vertices = vertices;
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Unit cube as a triangle soup: 12 faces, 36 unshared corners, wound
+    /// outward
+    fn cube_soup() -> TriangleMesh {
+        let corner = |i: usize| {
+            Point3::new((i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64)
+        };
+        let quads = [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ];
+        let mut mesh = TriangleMesh::new();
+        for [a, b, c, d] in quads {
+            for tri in [[a, b, c], [a, c, d]] {
+                let start = mesh.vertices.len();
+                for i in tri {
+                    mesh.add_vertex(Vertex::new(corner(i)));
+                }
+                mesh.add_face(TriangleFace::new(start, start + 1, start + 2));
+            }
+        }
+        mesh
+    }
+
+    fn signed_volume(mesh: &TriangleMesh) -> f64 {
+        mesh.faces
+            .iter()
+            .map(|f| {
+                let [a, b, c] = f.vertices.map(|v| mesh.vertices[v].position.coords);
+                a.dot(&b.cross(&c)) / 6.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_repair_damaged_cube() {
+        let mut mesh = cube_soup();
+        // Remove the top (two faces), flip a side face, double a face and
+        // add a collapsed one
+        mesh.faces.remove(3);
+        mesh.faces.remove(2);
+        mesh.faces[4] = mesh.faces[4].reverse();
+        mesh.faces.push(mesh.faces[0]);
+        mesh.faces.push(TriangleFace::new(0, 0, 1));
+
+        let repair = MeshRepair::new();
+        let before = repair.analyze(&mesh);
+        assert_eq!(before.duplicate_vertices, 28);
+        assert_eq!(before.degenerate_faces, 1);
+        assert_eq!(before.duplicate_faces, 1);
+        assert!(!before.is_watertight());
+
+        let report = repair.repair(&mut mesh);
+        assert_eq!(report.before, before);
+        assert_eq!(report.merged_vertices, 28);
+        assert_eq!(report.removed_degenerate_faces, 1);
+        assert_eq!(report.removed_duplicate_faces, 1);
+        assert_eq!(report.flipped_faces, 1);
+        assert_eq!(report.filled_holes, 1);
+        assert_eq!(report.added_faces, 2);
+        assert!(report.after.is_watertight(), "{:?}", report.after);
+        assert_eq!(mesh.vertices.len(), 8);
+        assert_eq!(mesh.faces.len(), 12);
+        assert!((signed_volume(&mesh) - 1.0).abs() < 1e-9);
+        assert!(report.changes().iter().any(|c| c == "filled holes: 1"));
+
+        // A repaired mesh needs nothing more
+        let again = repair.repair(&mut mesh);
+        assert!(!again.changed(), "{:?}", again.changes());
+    }
+
+    #[test]
+    fn test_inside_out_and_non_manifold() {
+        let mut mesh = cube_soup();
+        mesh.reverse_normals();
+        let report = MeshRepair::new().repair(&mut mesh);
+        assert_eq!(report.inverted_shells, 1);
+        assert!((signed_volume(&mesh) - 1.0).abs() < 1e-9);
+
+        // A fin: a third face on one of the cube's edges
+        let mut mesh = cube_soup();
+        mesh.merge_vertices(1e-6);
+        let [a, b, _] = mesh.faces[0].vertices;
+        let tip = mesh.add_vertex(Vertex::new(Point3::new(-1.0, -1.0, 0.5)));
+        mesh.add_face(TriangleFace::new(a, b, tip));
+        let repair = MeshRepair::new();
+        assert_eq!(repair.analyze(&mesh).non_manifold_edges, 1);
+        let report = repair.repair(&mut mesh);
+        assert_eq!(report.removed_non_manifold_faces, 1);
+        assert_eq!(report.removed_unused_vertices, 1);
+        assert!(report.after.is_watertight());
+
+        // Two cubes touching at a corner share a vertex but no edge
+        let mut mesh = cube_soup();
+        let offset = mesh.vertices.len();
+        for v in cube_soup().vertices {
+            mesh.add_vertex(Vertex::new(v.position + Vector3::new(1.0, 1.0, 1.0)));
+        }
+        for f in cube_soup().faces {
+            let [a, b, c] = f.vertices.map(|v| v + offset);
+            mesh.add_face(TriangleFace::new(a, b, c));
+        }
+        let report = MeshRepair::new().repair(&mut mesh);
+        assert_eq!(report.split_vertices, 1);
+        assert_eq!(mesh.vertices.len(), 16);
+        assert!(report.after.is_watertight());
+    }
+}
@@ -0,0 +1,354 @@
+//! Polyline simplification and smoothing
+//!
+//! Cleans up dense, noisy polylines such as scanned outlines, traced
+//! images and GPS tracks:
+//! - [`simplify`] drops vertices with the Douglas-Peucker algorithm, keeping
+//!   the result within a tolerance of the original; [`simplify_indices`]
+//!   returns the kept vertices by index so per-vertex data (timestamps,
+//!   elevations) can follow
+//! - [`smooth`] fairs a polyline by curvature flow, moving each vertex
+//!   against its discrete curvature with Taubin's shrink-free two-step
+//!   scheme. No vertex moves further than the tolerance from where it
+//!   started, the ends of open polylines stay put, and sharp corners can be
+//!   held
+//! - [`curvature`] gives the discrete curvature at each vertex
+//!
+//! Simplify after smoothing: fairing needs the dense vertices, and the
+//! faired curve simplifies to far fewer of them.
+
+use crate::core::precision::EPSILON;
+use crate::geometry::line::LineSegment2D;
+use crate::geometry::point::Point2D;
+use serde::{Deserialize, Serialize};
+
+/// Taubin pass-band frequency; noise above it is removed, shape below it
+/// is kept
+const PASS_BAND: f64 = 0.1;
+
+/// Smoothing settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SmoothOptions {
+    /// Smoothing passes; each pass shrinks then re-inflates the curve
+    pub iterations: usize,
+    /// Fraction of the curvature step taken per pass, in (0, 1)
+    pub strength: f64,
+    /// Vertices turning by more than this angle (radians) are corners and
+    /// stay where they are; `None` smooths through every vertex
+    pub corner_angle: Option<f64>,
+}
+
+impl Default for SmoothOptions {
+    fn default() -> Self {
+        Self {
+            iterations: 10,
+            strength: 0.5,
+            corner_angle: Some(std::f64::consts::FRAC_PI_3),
+        }
+    }
+}
+
+impl SmoothOptions {
+    /// Set the number of passes
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Set the step fraction per pass
+    pub fn with_strength(mut self, strength: f64) -> Self {
+        self.strength = strength;
+        self
+    }
+
+    /// Hold vertices turning by more than `angle`, or none
+    pub fn with_corner_angle(mut self, angle: Option<f64>) -> Self {
+        self.corner_angle = angle;
+        self
+    }
+}
+
+/// Simplify a polyline so that no dropped vertex lies further than
+/// `tolerance` from the result
+///
+/// A closed polyline is split at the vertex furthest from its first vertex
+/// and both halves simplified; the first vertex is then dropped too if it
+/// turned out to lie on a straight run.
+pub fn simplify(points: &[Point2D], tolerance: f64, closed: bool) -> Vec<Point2D> {
+    simplify_indices(points, tolerance, closed)
+        .into_iter()
+        .map(|i| points[i])
+        .collect()
+}
+
+/// Indices of the vertices [`simplify`] keeps, in order
+pub fn simplify_indices(points: &[Point2D], tolerance: f64, closed: bool) -> Vec<usize> {
+    let n = points.len();
+    if n < 3 {
+        return (0..n).collect();
+    }
+
+    let mut keep = vec![false; n + 1];
+    if closed {
+        // Index n stands for the first vertex closing the loop
+        let far = (1..n)
+            .max_by(|&a, &b| {
+                let da = points[0].distance_squared_to(&points[a]);
+                let db = points[0].distance_squared_to(&points[b]);
+                da.total_cmp(&db)
+            })
+            .unwrap_or(0);
+        let at = |i: usize| points[i % n];
+        keep[0] = true;
+        keep[far] = true;
+        douglas_peucker(&at, 0, far, tolerance, &mut keep);
+        douglas_peucker(&at, far, n, tolerance, &mut keep);
+
+        // The first vertex is only a seam; drop it if the chord between its
+        // kept neighbours covers everything it spanned
+        let last = (1..n).rev().find(|&i| keep[i]).unwrap_or(0);
+        let next = (1..n).find(|&i| keep[i]).unwrap_or(0);
+        if last != next {
+            let chord = LineSegment2D::new(points[last], points[next]);
+            if (last + 1..n + next).all(|i| chord.distance_to_point(&at(i)) <= tolerance) {
+                keep[0] = false;
+            }
+        }
+    } else {
+        let at = |i: usize| points[i];
+        keep[0] = true;
+        keep[n - 1] = true;
+        douglas_peucker(&at, 0, n - 1, tolerance, &mut keep);
+    }
+    (0..n).filter(|&i| keep[i]).collect()
+}
+
+/// Mark the vertices to keep between `first` and `last`; iterative so long
+/// tracks cannot overflow the stack
+fn douglas_peucker(at: &impl Fn(usize) -> Point2D, first: usize, last: usize, tolerance: f64, keep: &mut [bool]) {
+    let mut spans = vec![(first, last)];
+    while let Some((first, last)) = spans.pop() {
+        if last <= first + 1 {
+            continue;
+        }
+        let chord = LineSegment2D::new(at(first), at(last));
+        let (index, distance) =
+            (first + 1..last)
+                .map(|i| (i, chord.distance_to_point(&at(i))))
+                .fold(
+                    (first, 0.0),
+                    |best, candidate| {
+                        if candidate.1 > best.1 {
+                            candidate
+                        } else {
+                            best
+                        }
+                    },
+                );
+        if distance > tolerance {
+            keep[index] = true;
+            spans.push((first, index));
+            spans.push((index, last));
+        }
+    }
+}
+
+/// Smooth a polyline, moving no vertex further than `tolerance` from its
+/// original position
+///
+/// Each pass moves every free vertex toward the length-weighted average of
+/// its neighbours, a step along the discrete curvature normal that leaves
+/// unevenly spaced collinear vertices where they are, then takes a
+/// slightly larger step back, which keeps the curve from shrinking. Open
+/// polylines keep their end points.
+pub fn smooth(points: &[Point2D], tolerance: f64, closed: bool, options: &SmoothOptions) -> Vec<Point2D> {
+    let n = points.len();
+    if n < 3 || tolerance <= 0.0 {
+        return points.to_vec();
+    }
+
+    let fixed: Vec<bool> = (0..n)
+        .map(|i| {
+            if !closed && (i == 0 || i == n - 1) {
+                return true;
+            }
+            let (prev, next) = neighbours(n, i, closed);
+            options
+                .corner_angle
+                .is_some_and(|limit| turning_angle(points[prev], points[i], points[next]) > limit)
+        })
+        .collect();
+
+    let lambda = options.strength.clamp(EPSILON, 1.0 - EPSILON);
+    let mu = 1.0 / (PASS_BAND - 1.0 / lambda);
+    let mut current = points.to_vec();
+    for _ in 0..options.iterations {
+        for factor in [lambda, mu] {
+            let previous = current.clone();
+            for i in (0..n).filter(|&i| !fixed[i]) {
+                let (prev, next) = neighbours(n, i, closed);
+                let step = umbrella(previous[prev], previous[i], previous[next]);
+                let moved = previous[i] + step * factor;
+                current[i] = clamp_to(points[i], moved, tolerance);
+            }
+        }
+    }
+    current
+}
+
+/// Signed discrete curvature at each vertex: the inverse radius of the
+/// circle through the vertex and its neighbours, positive for left turns.
+/// The ends of an open polyline have zero curvature.
+pub fn curvature(points: &[Point2D], closed: bool) -> Vec<f64> {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            if n < 3 || (!closed && (i == 0 || i == n - 1)) {
+                return 0.0;
+            }
+            let (prev, next) = neighbours(n, i, closed);
+            let (a, b, c) = (points[prev], points[i], points[next]);
+            let lengths = a.distance_to(&b) * b.distance_to(&c) * a.distance_to(&c);
+            if lengths < EPSILON {
+                0.0
+            } else {
+                2.0 * (b - a).cross(&(c - b)) / lengths
+            }
+        })
+        .collect()
+}
+
+fn neighbours(n: usize, i: usize, closed: bool) -> (usize, usize) {
+    if closed {
+        ((i + n - 1) % n, (i + 1) % n)
+    } else {
+        (i.saturating_sub(1), (i + 1).min(n - 1))
+    }
+}
+
+/// Angle between the incoming and outgoing directions at `b`
+fn turning_angle(a: Point2D, b: Point2D, c: Point2D) -> f64 {
+    let (u, v) = (b - a, c - b);
+    if u.dot(&u) < EPSILON || v.dot(&v) < EPSILON {
+        return 0.0;
+    }
+    u.cross(&v).atan2(u.dot(&v)).abs()
+}
+
+/// Displacement from `b` to the average of its neighbours weighted by
+/// inverse edge length
+fn umbrella(a: Point2D, b: Point2D, c: Point2D) -> Point2D {
+    let (la, lc) = (a.distance_to(&b), c.distance_to(&b));
+    if la < EPSILON || lc < EPSILON {
+        return (a + c) * 0.5 - b;
+    }
+    let (wa, wc) = (1.0 / la, 1.0 / lc);
+    ((a - b) * wa + (c - b) * wc) / (wa + wc)
+}
+
+/// `moved`, pulled back to within `tolerance` of `origin`
+fn clamp_to(origin: Point2D, moved: Point2D, tolerance: f64) -> Point2D {
+    let offset = moved - origin;
+    let distance = offset.dot(&offset).sqrt();
+    if distance > tolerance {
+        origin + offset * (tolerance / distance)
+    } else {
+        moved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    /// Deterministic noise in [-amplitude, amplitude]
+    fn noise(i: usize, amplitude: f64) -> f64 {
+        (((i * 7919) % 101) as f64 / 50.0 - 1.0) * amplitude
+    }
+
+    #[test]
+    fn test_simplify_open_track() {
+        // An L-shaped track with jitter along both legs
+        let mut track: Vec<Point2D> = (0..=50).map(|i| Point2D::new(i as f64, noise(i, 0.05))).collect();
+        track.extend((1..=50).map(|i| Point2D::new(50.0 + noise(i + 50, 0.05), i as f64)));
+
+        let kept = simplify_indices(&track, 0.1, false);
+        assert_eq!(kept, vec![0, 50, 100]);
+        assert_eq!(simplify(&track, 0.1, false).len(), 3);
+
+        // A tight tolerance keeps the jitter
+        assert!(simplify(&track, 0.01, false).len() > 20);
+    }
+
+    #[test]
+    fn test_simplify_closed_outline() {
+        // Scanned square, densely sampled, starting mid-edge
+        let mut outline = Vec::new();
+        let corners = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        for side in 0..4 {
+            let (x0, y0) = corners[side];
+            let (x1, y1) = corners[(side + 1) % 4];
+            for k in 0..10 {
+                let t = k as f64 / 10.0;
+                outline.push(Point2D::new(x0 + (x1 - x0) * t, y0 + (y1 - y0) * t));
+            }
+        }
+        outline.rotate_left(5);
+
+        let simplified = simplify(&outline, 0.01, true);
+        assert_eq!(simplified.len(), 4);
+        for (x, y) in corners {
+            assert!(simplified.iter().any(|p| p.approx_eq(&Point2D::new(x, y))));
+        }
+    }
+
+    #[test]
+    fn test_smooth_noisy_circle() {
+        let n = 90;
+        let ring: Vec<Point2D> = (0..n)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f64 / n as f64;
+                Point2D::from_polar(10.0 + noise(i, 0.2), angle)
+            })
+            .collect();
+
+        let options = SmoothOptions::default().with_corner_angle(None);
+        let smoothed = smooth(&ring, 0.3, true, &options);
+        assert_eq!(smoothed.len(), n);
+        for (before, after) in ring.iter().zip(&smoothed) {
+            assert!(before.distance_to(after) <= 0.3 + EPSILON);
+        }
+
+        // The radius evens out without the ring shrinking
+        let spread = |points: &[Point2D]| {
+            let radii: Vec<f64> = points.iter().map(|p| p.distance_to_origin()).collect();
+            let mean = radii.iter().sum::<f64>() / radii.len() as f64;
+            let deviation = radii.iter().map(|r| (r - mean).abs()).fold(0.0, f64::max);
+            (mean, deviation)
+        };
+        let (_, noisy) = spread(&ring);
+        let (mean, deviation) = spread(&smoothed);
+        assert!(deviation < noisy / 2.0);
+        assert!((mean - 10.0).abs() < 0.1);
+
+        let bumpiness = |points: &[Point2D]| curvature(points, true).iter().map(|k| (k - 0.1).abs()).sum::<f64>();
+        assert!(bumpiness(&smoothed) < bumpiness(&ring) / 2.0);
+    }
+
+    #[test]
+    fn test_smooth_holds_ends_and_corners() {
+        let mut path: Vec<Point2D> = (0..=20).map(|i| Point2D::new(i as f64, noise(i, 0.1))).collect();
+        path.extend((1..=20).map(|i| Point2D::new(20.0, i as f64)));
+
+        let smoothed = smooth(&path, 0.5, false, &SmoothOptions::default());
+        assert_eq!(smoothed[0], path[0]);
+        assert_eq!(smoothed[40], path[40]);
+        assert_eq!(smoothed[20], path[20]);
+        assert!(smoothed[5].y.abs() < path[5].y.abs() || path[5].y.abs() < EPSILON);
+
+        // Without corner detection the corner is rounded, but only so far
+        let rounded = smooth(&path, 0.5, false, &SmoothOptions::default().with_corner_angle(None));
+        let moved = rounded[20].distance_to(&path[20]);
+        assert!(moved > 0.1 && moved <= 0.5 + EPSILON);
+    }
+}
@@ -0,0 +1,778 @@
+//! Spatial index for entity queries
+//!
+//! `SpatialIndex` is a dynamic bounding volume hierarchy: a binary tree of
+//! axis-aligned boxes whose leaves are the indexed items. Inserts descend to
+//! the cheapest sibling and rotations keep the tree height-balanced, so box,
+//! nearest-neighbour and ray queries visit O(log n) nodes for typical
+//! drawings instead of every entity. Boxes may be flat or degenerate (2D
+//! geometry lives at z = 0), which is why the insertion cost is the sum of
+//! the box extents rather than its surface area.
+
+use crate::core::primitives::{BoundingBox3, Point3, Ray3};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+/// Dynamic bounding volume hierarchy keyed by `K`
+#[derive(Debug, Clone)]
+pub struct SpatialIndex<K> {
+    nodes: Vec<Node<K>>,
+    free: Vec<usize>,
+    root: Option<usize>,
+    leaves: HashMap<K, usize>,
+}
+
+#[derive(Debug, Clone)]
+struct Node<K> {
+    bounds: BoundingBox3,
+    parent: Option<usize>,
+    /// Leaves are 0, free slots -1
+    height: i32,
+    kind: NodeKind<K>,
+}
+
+#[derive(Debug, Clone)]
+enum NodeKind<K> {
+    Leaf(K),
+    Branch([usize; 2]),
+    Free,
+}
+
+impl<K> Default for SpatialIndex<K> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: None,
+            leaves: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Copy + Eq + Hash> SpatialIndex<K> {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bulk-load an index, which gives a better tree than inserting one by one
+    ///
+    /// Later duplicates of a key replace earlier ones.
+    pub fn from_items(items: impl IntoIterator<Item = (K, BoundingBox3)>) -> Self {
+        let mut index = Self::new();
+        let mut latest: HashMap<K, BoundingBox3> = HashMap::new();
+        let mut order = Vec::new();
+        for (key, bounds) in items {
+            if latest.insert(key, normalized(&bounds)).is_none() {
+                order.push(key);
+            }
+        }
+        let mut leaves: Vec<usize> = order
+            .into_iter()
+            .map(|key| {
+                let leaf = index.allocate(latest[&key], 0, NodeKind::Leaf(key));
+                index.leaves.insert(key, leaf);
+                leaf
+            })
+            .collect();
+        if !leaves.is_empty() {
+            let root = index.build(&mut leaves);
+            index.root = Some(root);
+        }
+        index
+    }
+
+    /// Number of indexed items
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Whether the index is empty
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Remove every item
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+        self.root = None;
+        self.leaves.clear();
+    }
+
+    /// Whether `key` is indexed
+    pub fn contains(&self, key: &K) -> bool {
+        self.leaves.contains_key(key)
+    }
+
+    /// Bounds stored for `key`
+    pub fn get(&self, key: &K) -> Option<&BoundingBox3> {
+        self.leaves.get(key).map(|&leaf| &self.nodes[leaf].bounds)
+    }
+
+    /// Bounds of everything in the index
+    pub fn total_bounds(&self) -> Option<BoundingBox3> {
+        self.root.map(|root| self.nodes[root].bounds)
+    }
+
+    /// Indexed keys and their bounds, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (K, &BoundingBox3)> + '_ {
+        self.leaves
+            .iter()
+            .map(move |(&key, &leaf)| (key, &self.nodes[leaf].bounds))
+    }
+
+    /// Insert `key`, or move it if it is already indexed
+    pub fn insert(&mut self, key: K, bounds: BoundingBox3) {
+        let bounds = normalized(&bounds);
+        if let Some(&leaf) = self.leaves.get(&key) {
+            if same_box(&self.nodes[leaf].bounds, &bounds) {
+                return;
+            }
+            self.remove(&key);
+        }
+
+        let leaf = self.allocate(bounds, 0, NodeKind::Leaf(key));
+        self.leaves.insert(key, leaf);
+        self.insert_leaf(leaf);
+    }
+
+    /// Move `key` to new bounds; alias of [`insert`](Self::insert)
+    pub fn update(&mut self, key: K, bounds: BoundingBox3) {
+        self.insert(key, bounds);
+    }
+
+    /// Remove `key`, returning its bounds
+    pub fn remove(&mut self, key: &K) -> Option<BoundingBox3> {
+        let leaf = self.leaves.remove(key)?;
+        let bounds = self.nodes[leaf].bounds;
+        self.remove_leaf(leaf);
+        self.release(leaf);
+        Some(bounds)
+    }
+
+    /// Rebuild the tree from scratch with the bulk loader
+    pub fn rebuild(&mut self) {
+        let items: Vec<_> = self.iter().map(|(key, bounds)| (key, *bounds)).collect();
+        *self = Self::from_items(items);
+    }
+
+    /// Keys whose bounds intersect `region` (touching counts)
+    pub fn query_box(&self, region: &BoundingBox3) -> Vec<K> {
+        let mut found = Vec::new();
+        self.visit(
+            |bounds| bounds.intersects(region),
+            |key, _| found.push(key),
+        );
+        found
+    }
+
+    /// Keys whose bounds come within `tolerance` of `point`
+    pub fn query_point(&self, point: &Point3, tolerance: f64) -> Vec<K> {
+        let mut found = Vec::new();
+        self.visit(
+            |bounds| box_distance(bounds, point) <= tolerance,
+            |key, _| found.push(key),
+        );
+        found
+    }
+
+    /// Keys whose bounds `ray` enters within `max_t`, nearest first
+    ///
+    /// Each key is paired with the ray parameter where it enters the box,
+    /// which is 0 when the ray starts inside.
+    pub fn query_ray(&self, ray: &Ray3, max_t: f64) -> Vec<(K, f64)> {
+        let mut found = Vec::new();
+        self.visit(
+            |bounds| ray_entry(ray, bounds).is_some_and(|t| t <= max_t),
+            |key, bounds| {
+                if let Some(t) = ray_entry(ray, bounds) {
+                    found.push((key, t));
+                }
+            },
+        );
+        found.sort_by(|a, b| a.1.total_cmp(&b.1));
+        found
+    }
+
+    /// Key whose bounds are closest to `point`, within `max_distance`
+    pub fn nearest(&self, point: &Point3, max_distance: f64) -> Option<(K, f64)> {
+        self.nearest_by(point, max_distance, |_, bounds| Some(box_distance(bounds, point)))
+    }
+
+    /// Nearest key under a custom distance
+    ///
+    /// `distance` receives each candidate with its bounds and returns the
+    /// exact distance, or `None` to skip it. It must never be less than the
+    /// distance from `point` to the bounds, which is what prunes the search.
+    /// Ties go to the key found first.
+    pub fn nearest_by<F>(&self, point: &Point3, max_distance: f64, mut distance: F) -> Option<(K, f64)>
+    where
+        F: FnMut(K, &BoundingBox3) -> Option<f64>,
+    {
+        let root = self.root?;
+        let mut best: Option<(K, f64)> = None;
+        let mut queue = BinaryHeap::new();
+        queue.push(Candidate {
+            distance: box_distance(&self.nodes[root].bounds, point),
+            node: root,
+        });
+
+        while let Some(Candidate { distance: lower, node }) = queue.pop() {
+            let limit = best.map_or(max_distance, |(_, d)| d);
+            if lower > limit || (best.is_some() && lower == limit) {
+                break;
+            }
+            match &self.nodes[node].kind {
+                NodeKind::Leaf(key) => {
+                    if let Some(d) = distance(*key, &self.nodes[node].bounds) {
+                        if d <= max_distance && best.is_none_or(|(_, b)| d < b) {
+                            best = Some((*key, d));
+                        }
+                    }
+                }
+                NodeKind::Branch(children) => {
+                    for &child in children {
+                        queue.push(Candidate {
+                            distance: box_distance(&self.nodes[child].bounds, point),
+                            node: child,
+                        });
+                    }
+                }
+                NodeKind::Free => {}
+            }
+        }
+        best
+    }
+
+    /// Up to `k` keys nearest to `point` by bounds distance, closest first
+    pub fn nearest_k(&self, point: &Point3, k: usize) -> Vec<(K, f64)> {
+        let mut found = Vec::new();
+        let Some(root) = self.root else {
+            return found;
+        };
+        let mut queue = BinaryHeap::new();
+        queue.push(Candidate {
+            distance: box_distance(&self.nodes[root].bounds, point),
+            node: root,
+        });
+
+        // Popped leaves come out in distance order, since a branch is never
+        // farther than its children
+        while let Some(Candidate { distance, node }) = queue.pop() {
+            if found.len() >= k {
+                break;
+            }
+            match &self.nodes[node].kind {
+                NodeKind::Leaf(key) => found.push((*key, distance)),
+                NodeKind::Branch(children) => {
+                    for &child in children {
+                        queue.push(Candidate {
+                            distance: box_distance(&self.nodes[child].bounds, point),
+                            node: child,
+                        });
+                    }
+                }
+                NodeKind::Free => {}
+            }
+        }
+        found
+    }
+
+    /// Depth-first walk calling `hit` for every leaf whose path passes `enter`
+    fn visit(&self, enter: impl Fn(&BoundingBox3) -> bool, mut hit: impl FnMut(K, &BoundingBox3)) {
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            if !enter(&node.bounds) {
+                continue;
+            }
+            match &node.kind {
+                NodeKind::Leaf(key) => hit(*key, &node.bounds),
+                NodeKind::Branch(children) => stack.extend(children),
+                NodeKind::Free => {}
+            }
+        }
+    }
+
+    fn allocate(&mut self, bounds: BoundingBox3, height: i32, kind: NodeKind<K>) -> usize {
+        let node = Node {
+            bounds,
+            parent: None,
+            height,
+            kind,
+        };
+        match self.free.pop() {
+            Some(slot) => {
+                self.nodes[slot] = node;
+                slot
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn release(&mut self, node: usize) {
+        self.nodes[node].kind = NodeKind::Free;
+        self.nodes[node].parent = None;
+        self.nodes[node].height = -1;
+        self.free.push(node);
+    }
+
+    fn children(&self, node: usize) -> [usize; 2] {
+        match self.nodes[node].kind {
+            NodeKind::Branch(children) => children,
+            _ => unreachable!("spatial index node {node} is not a branch"),
+        }
+    }
+
+    fn set_children(&mut self, node: usize, children: [usize; 2]) {
+        self.nodes[node].kind = NodeKind::Branch(children);
+    }
+
+    fn insert_leaf(&mut self, leaf: usize) {
+        let Some(root) = self.root else {
+            self.root = Some(leaf);
+            return;
+        };
+
+        // Descend towards the sibling that grows the tree the least
+        let bounds = self.nodes[leaf].bounds;
+        let mut sibling = root;
+        while let NodeKind::Branch(children) = self.nodes[sibling].kind {
+            let current = &self.nodes[sibling].bounds;
+            let combined = cost(&union(current, &bounds));
+            let here = 2.0 * combined;
+            let inherited = 2.0 * (combined - cost(current));
+
+            let descend_cost = |child: usize| {
+                let child = &self.nodes[child];
+                let grown = cost(&union(&child.bounds, &bounds));
+                match child.kind {
+                    NodeKind::Leaf(_) => grown + inherited,
+                    _ => grown - cost(&child.bounds) + inherited,
+                }
+            };
+            let cost0 = descend_cost(children[0]);
+            let cost1 = descend_cost(children[1]);
+            if here < cost0 && here < cost1 {
+                break;
+            }
+            sibling = if cost0 <= cost1 { children[0] } else { children[1] };
+        }
+
+        let old_parent = self.nodes[sibling].parent;
+        let merged = union(&self.nodes[sibling].bounds, &bounds);
+        let height = self.nodes[sibling].height + 1;
+        let parent = self.allocate(merged, height, NodeKind::Branch([sibling, leaf]));
+        self.nodes[parent].parent = old_parent;
+        self.nodes[sibling].parent = Some(parent);
+        self.nodes[leaf].parent = Some(parent);
+        match old_parent {
+            Some(grandparent) => self.replace_child(grandparent, sibling, parent),
+            None => self.root = Some(parent),
+        }
+
+        self.refit(Some(parent));
+    }
+
+    fn remove_leaf(&mut self, leaf: usize) {
+        if self.root == Some(leaf) {
+            self.root = None;
+            return;
+        }
+
+        let parent = self.nodes[leaf].parent.expect("non-root leaf has a parent");
+        let [a, b] = self.children(parent);
+        let sibling = if a == leaf { b } else { a };
+        let grandparent = self.nodes[parent].parent;
+        self.nodes[sibling].parent = grandparent;
+        match grandparent {
+            Some(grandparent) => {
+                self.replace_child(grandparent, parent, sibling);
+                self.release(parent);
+                self.refit(Some(grandparent));
+            }
+            None => {
+                self.root = Some(sibling);
+                self.release(parent);
+            }
+        }
+    }
+
+    fn replace_child(&mut self, parent: usize, old: usize, new: usize) {
+        let mut children = self.children(parent);
+        if children[0] == old {
+            children[0] = new;
+        } else {
+            children[1] = new;
+        }
+        self.set_children(parent, children);
+    }
+
+    /// Rebalance and recompute bounds from `node` up to the root
+    fn refit(&mut self, mut node: Option<usize>) {
+        while let Some(index) = node {
+            let index = self.balance(index);
+            let [a, b] = self.children(index);
+            self.nodes[index].bounds = union(&self.nodes[a].bounds, &self.nodes[b].bounds);
+            self.nodes[index].height = 1 + self.nodes[a].height.max(self.nodes[b].height);
+            node = self.nodes[index].parent;
+        }
+    }
+
+    /// Rotate a child up if the subtree at `a` is out of balance; returns the
+    /// subtree's new root
+    fn balance(&mut self, a: usize) -> usize {
+        if self.nodes[a].height < 2 {
+            return a;
+        }
+        let [b, c] = self.children(a);
+        let skew = self.nodes[c].height - self.nodes[b].height;
+        if skew > 1 {
+            self.rotate_up(a, 1)
+        } else if skew < -1 {
+            self.rotate_up(a, 0)
+        } else {
+            a
+        }
+    }
+
+    /// Promote child `side` of `a` (which must be a branch) above `a`
+    fn rotate_up(&mut self, a: usize, side: usize) -> usize {
+        let up = self.children(a)[side];
+        let other = self.children(a)[1 - side];
+        let [f, g] = self.children(up);
+
+        self.nodes[up].parent = self.nodes[a].parent;
+        self.nodes[a].parent = Some(up);
+        match self.nodes[up].parent {
+            Some(parent) => self.replace_child(parent, a, up),
+            None => self.root = Some(up),
+        }
+
+        // The taller grandchild stays with `up`, the shorter moves down to `a`
+        let (keep, give) = if self.nodes[f].height > self.nodes[g].height {
+            (f, g)
+        } else {
+            (g, f)
+        };
+        self.set_children(up, [a, keep]);
+        let mut children = [other, other];
+        children[side] = give;
+        self.set_children(a, children);
+        self.nodes[give].parent = Some(a);
+
+        self.nodes[a].bounds = union(&self.nodes[other].bounds, &self.nodes[give].bounds);
+        self.nodes[a].height = 1 + self.nodes[other].height.max(self.nodes[give].height);
+        self.nodes[up].bounds = union(&self.nodes[a].bounds, &self.nodes[keep].bounds);
+        self.nodes[up].height = 1 + self.nodes[a].height.max(self.nodes[keep].height);
+        up
+    }
+
+    /// Top-down median split on the widest axis of the leaf centres
+    fn build(&mut self, leaves: &mut [usize]) -> usize {
+        if leaves.len() == 1 {
+            return leaves[0];
+        }
+
+        let centers = leaves.iter().map(|&leaf| self.nodes[leaf].bounds.center());
+        let mut spread = BoundingBox3::new(
+            Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            Point3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        );
+        for center in centers {
+            spread.expand_to_include(&center);
+        }
+        let extents = [spread.width(), spread.height(), spread.depth()];
+        let axis = (0..3)
+            .max_by(|&i, &j| extents[i].total_cmp(&extents[j]))
+            .unwrap_or(0);
+
+        let mid = leaves.len() / 2;
+        leaves.select_nth_unstable_by(mid, |&l, &r| {
+            let l = self.nodes[l].bounds.center()[axis];
+            let r = self.nodes[r].bounds.center()[axis];
+            l.total_cmp(&r)
+        });
+        let (left, right) = leaves.split_at_mut(mid);
+        let left = self.build(left);
+        let right = self.build(right);
+
+        let bounds = union(&self.nodes[left].bounds, &self.nodes[right].bounds);
+        let height = 1 + self.nodes[left].height.max(self.nodes[right].height);
+        let node = self.allocate(bounds, height, NodeKind::Branch([left, right]));
+        self.nodes[left].parent = Some(node);
+        self.nodes[right].parent = Some(node);
+        node
+    }
+}
+
+/// Node awaiting a visit in best-first search, ordered closest first
+struct Candidate {
+    distance: f64,
+    node: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+/// Distance from `point` to the nearest point of `bounds` (0 inside)
+pub fn box_distance(bounds: &BoundingBox3, point: &Point3) -> f64 {
+    let gap = |low: f64, high: f64, value: f64| (low - value).max(value - high).max(0.0);
+    let dx = gap(bounds.min.x, bounds.max.x, point.x);
+    let dy = gap(bounds.min.y, bounds.max.y, point.y);
+    let dz = gap(bounds.min.z, bounds.max.z, point.z);
+    (dx * dx + dy * dy + dz * dz).sqrt()
+}
+
+/// Ray parameter where `ray` enters `bounds`, 0 if it starts inside
+///
+/// Unlike [`BoundingBox3::intersect_ray`] this handles axis-parallel rays and
+/// flat boxes, which 2D entities always are.
+pub fn ray_entry(ray: &Ray3, bounds: &BoundingBox3) -> Option<f64> {
+    let mut t_min = 0.0_f64;
+    let mut t_max = f64::INFINITY;
+    for axis in 0..3 {
+        let origin = ray.origin[axis];
+        let direction = ray.direction[axis];
+        let (low, high) = (bounds.min[axis], bounds.max[axis]);
+        if direction.abs() < f64::EPSILON {
+            if origin < low || origin > high {
+                return None;
+            }
+            continue;
+        }
+        let t1 = (low - origin) / direction;
+        let t2 = (high - origin) / direction;
+        t_min = t_min.max(t1.min(t2));
+        t_max = t_max.min(t1.max(t2));
+        if t_min > t_max {
+            return None;
+        }
+    }
+    Some(t_min)
+}
+
+fn union(a: &BoundingBox3, b: &BoundingBox3) -> BoundingBox3 {
+    BoundingBox3::new(
+        Point3::new(a.min.x.min(b.min.x), a.min.y.min(b.min.y), a.min.z.min(b.min.z)),
+        Point3::new(a.max.x.max(b.max.x), a.max.y.max(b.max.y), a.max.z.max(b.max.z)),
+    )
+}
+
+/// Insertion cost; the extent sum stays meaningful for flat boxes
+fn cost(bounds: &BoundingBox3) -> f64 {
+    bounds.width() + bounds.height() + bounds.depth()
+}
+
+/// Swap inverted corners so min <= max on every axis
+fn normalized(bounds: &BoundingBox3) -> BoundingBox3 {
+    union(
+        &BoundingBox3::new(bounds.min, bounds.min),
+        &BoundingBox3::new(bounds.max, bounds.max),
+    )
+}
+
+fn same_box(a: &BoundingBox3, b: &BoundingBox3) -> bool {
+    a.min == b.min && a.max == b.max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector3;
+
+    fn square(x: f64, y: f64, size: f64) -> BoundingBox3 {
+        BoundingBox3::new(Point3::new(x, y, 0.0), Point3::new(x + size, y + size, 0.0))
+    }
+
+    /// Deterministic pseudo-random boxes for cross-checking against brute force
+    fn scattered(count: usize) -> Vec<(usize, BoundingBox3)> {
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 10_000) as f64 / 10.0
+        };
+        (0..count)
+            .map(|i| {
+                let (x, y, z) = (next(), next(), next() / 10.0);
+                let (w, h) = (next() / 50.0, next() / 50.0);
+                (i, BoundingBox3::new(Point3::new(x, y, z), Point3::new(x + w, y + h, z)))
+            })
+            .collect()
+    }
+
+    fn check_structure<K: Copy + Eq + Hash>(index: &SpatialIndex<K>) {
+        let Some(root) = index.root else {
+            assert!(index.leaves.is_empty());
+            return;
+        };
+        let mut leaves = 0;
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            match index.nodes[node].kind {
+                NodeKind::Leaf(_) => leaves += 1,
+                NodeKind::Branch([a, b]) => {
+                    for child in [a, b] {
+                        assert_eq!(index.nodes[child].parent, Some(node));
+                        let outer = &index.nodes[node].bounds;
+                        let inner = &index.nodes[child].bounds;
+                        assert!(outer.contains(&inner.min) && outer.contains(&inner.max));
+                    }
+                    let (ha, hb) = (index.nodes[a].height, index.nodes[b].height);
+                    assert!((ha - hb).abs() <= 1, "unbalanced at {node}");
+                    stack.extend([a, b]);
+                }
+                NodeKind::Free => panic!("free node {node} reachable"),
+            }
+        }
+        assert_eq!(leaves, index.len());
+    }
+
+    #[test]
+    fn test_queries_match_brute_force() {
+        let items = scattered(600);
+        let mut incremental = SpatialIndex::new();
+        for (key, bounds) in &items {
+            incremental.insert(*key, *bounds);
+        }
+        let bulk = SpatialIndex::from_items(items.iter().copied());
+        check_structure(&incremental);
+        check_structure(&bulk);
+
+        let region = BoundingBox3::new(Point3::new(200.0, 300.0, -1.0), Point3::new(450.0, 520.0, 100.0));
+        let mut expected: Vec<usize> =
+            items.iter().filter(|(_, b)| b.intersects(&region)).map(|(k, _)| *k).collect();
+        expected.sort();
+        for index in [&incremental, &bulk] {
+            let mut found = index.query_box(&region);
+            found.sort();
+            assert_eq!(found, expected);
+        }
+
+        let probe = Point3::new(512.0, 256.0, 3.0);
+        let brute = items
+            .iter()
+            .map(|(k, b)| (*k, box_distance(b, &probe)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        for index in [&incremental, &bulk] {
+            let (_, distance) = index.nearest(&probe, f64::INFINITY).unwrap();
+            assert!((distance - brute.1).abs() < 1e-12);
+            let nearest = index.nearest_k(&probe, 5);
+            assert_eq!(nearest.len(), 5);
+            assert!((nearest[0].1 - brute.1).abs() < 1e-12);
+            assert!(nearest.windows(2).all(|w| w[0].1 <= w[1].1));
+        }
+        assert!(incremental.nearest(&probe, brute.1 / 2.0).is_none() || brute.1 == 0.0);
+
+        // Centre distance is never below box distance, so it can drive the search
+        let by_center = incremental
+            .nearest_by(&probe, f64::INFINITY, |_, b| Some(nalgebra::distance(&b.center(), &probe)))
+            .unwrap();
+        let brute_center = items
+            .iter()
+            .map(|(_, b)| nalgebra::distance(&b.center(), &probe))
+            .fold(f64::INFINITY, f64::min);
+        assert!((by_center.1 - brute_center).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_remove_and_update() {
+        let items = scattered(200);
+        let mut index = SpatialIndex::from_items(items.iter().copied());
+        for (key, _) in items.iter().filter(|(k, _)| k % 3 == 0) {
+            assert!(index.remove(key).is_some());
+        }
+        assert!(index.remove(&0).is_none());
+        for (key, bounds) in items.iter().filter(|(k, _)| k % 3 == 1) {
+            let moved = BoundingBox3::new(bounds.min + Vector3::new(2000.0, 0.0, 0.0), bounds.max + Vector3::new(2000.0, 0.0, 0.0));
+            index.update(*key, moved);
+        }
+        check_structure(&index);
+        assert_eq!(index.len(), items.len() - items.len().div_ceil(3));
+
+        let far = BoundingBox3::new(Point3::new(1500.0, -10.0, -10.0), Point3::new(4000.0, 2000.0, 200.0));
+        let mut moved = index.query_box(&far);
+        moved.sort();
+        let expected: Vec<usize> = items.iter().map(|(k, _)| *k).filter(|k| k % 3 == 1).collect();
+        assert_eq!(moved, expected);
+
+        index.rebuild();
+        check_structure(&index);
+        assert_eq!(index.query_box(&far).len(), expected.len());
+
+        index.clear();
+        assert!(index.is_empty());
+        assert!(index.total_bounds().is_none());
+        assert!(index.query_point(&Point3::origin(), 1e9).is_empty());
+    }
+
+    #[test]
+    fn test_ray_and_point_queries() {
+        let mut index = SpatialIndex::new();
+        index.insert("a", square(0.0, 0.0, 1.0));
+        index.insert("b", square(5.0, 0.0, 1.0));
+        index.insert("c", square(10.0, 0.0, 1.0));
+        index.insert("d", square(0.0, 10.0, 1.0));
+
+        // Horizontal ray in the z = 0 plane hits the flat squares in order
+        let ray = Ray3::new(Point3::new(-1.0, 0.5, 0.0), Vector3::new(1.0, 0.0, 0.0));
+        let hits = index.query_ray(&ray, 8.0);
+        assert_eq!(hits.iter().map(|h| h.0).collect::<Vec<_>>(), vec!["a", "b"]);
+        assert!((hits[1].1 - 6.0).abs() < 1e-12);
+
+        // A view ray straight down only hits the square under it
+        let down = Ray3::new(Point3::new(10.5, 0.5, 50.0), Vector3::new(0.0, 0.0, -1.0));
+        let hits = index.query_ray(&down, f64::INFINITY);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0, "c");
+        assert!((hits[0].1 - 50.0).abs() < 1e-12);
+
+        let mut near = index.query_point(&Point3::new(3.0, 0.5, 0.0), 2.0);
+        near.sort();
+        assert_eq!(near, vec!["a", "b"]);
+        assert_eq!(index.nearest(&Point3::new(0.5, 8.0, 0.0), 5.0).map(|n| n.0), Some("d"));
+
+        // Re-inserting with identical bounds is a no-op, new bounds move the key
+        index.insert("a", square(0.0, 0.0, 1.0));
+        index.insert("d", square(20.0, 20.0, 1.0));
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.get(&"d").unwrap().min, Point3::new(20.0, 20.0, 0.0));
+        let total = index.total_bounds().unwrap();
+        assert_eq!((total.min.x, total.max.y), (0.0, 21.0));
+
+        // Inserting in sorted order is the worst case for an unbalanced tree
+        let mut row = SpatialIndex::new();
+        for i in 0..1000 {
+            row.insert(i, square(i as f64, 0.0, 0.5));
+        }
+        check_structure(&row);
+        assert!(row.root.is_some_and(|root| row.nodes[root].height < 20));
+    }
+}
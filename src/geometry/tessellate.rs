@@ -0,0 +1,749 @@
+//! Polygon tessellation for filled rendering
+//!
+//! [`tessellate`] turns an outer boundary and any number of hole loops into
+//! an indexed triangle mesh by ear clipping. Each hole is first joined to
+//! the boundary with a bridge edge to the nearest visible boundary vertex,
+//! leaving one loop to clip. Polygons that ear clipping alone gets stuck on
+//! (touching or slightly self-intersecting loops) are handled by removing
+//! local self-intersections and, failing that, splitting along a diagonal
+//! and tessellating each half.
+//!
+//! Unlike [`Triangulation::from_polygon`](super::delaunay::Triangulation::from_polygon)
+//! nothing is inserted or moved: the mesh vertices are exactly the input
+//! points, so boundary-aligned attributes carry over unchanged, and the
+//! cost stays close to linear for the fills and hatches the renderer draws.
+//!
+//! [`TessellationSettings`] holds the tolerances curved geometry is faceted
+//! to. Documents keep one set for display and export, exports may override
+//! it, and the engine3d surface tessellator refines until every facet meets
+//! it.
+
+use crate::geometry::point::Point2D;
+use crate::geometry::polygon::Polygon2D;
+use serde::{Deserialize, Serialize};
+
+/// Indexed triangle mesh of a filled region
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FillMesh {
+    /// The outer boundary's points followed by each hole's, as given
+    pub vertices: Vec<Point2D>,
+    /// Counter-clockwise triangles, three indices into `vertices` each
+    pub indices: Vec<u32>,
+}
+
+impl FillMesh {
+    /// Tessellate a polygon and its holes
+    pub fn from_polygon(polygon: &Polygon2D) -> Self {
+        tessellate(&polygon.vertices, &polygon.holes)
+    }
+
+    /// Number of triangles
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Whether there is nothing to fill
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Triangle corners
+    pub fn triangles(&self) -> impl Iterator<Item = [Point2D; 3]> + '_ {
+        self.indices.chunks_exact(3).map(|t| {
+            [
+                self.vertices[t[0] as usize],
+                self.vertices[t[1] as usize],
+                self.vertices[t[2] as usize],
+            ]
+        })
+    }
+
+    /// Total area of the triangles
+    pub fn area(&self) -> f64 {
+        self.triangles()
+            .map(|[a, b, c]| (b - a).cross(&(c - a)) / 2.0)
+            .sum()
+    }
+
+    /// Vertex positions as `f32` pairs for upload
+    pub fn positions(&self) -> Vec<[f32; 2]> {
+        self.vertices
+            .iter()
+            .map(|p| [p.x as f32, p.y as f32])
+            .collect()
+    }
+}
+
+/// Tessellate the region inside `outer` and outside every loop in `holes`
+///
+/// Loops may be in either orientation and may repeat their first point at
+/// the end. Holes should lie inside the outer boundary and not overlap each
+/// other. Loops with fewer than three distinct points are ignored; the mesh
+/// is empty if the outer boundary is one of them.
+pub fn tessellate(outer: &[Point2D], holes: &[Vec<Point2D>]) -> FillMesh {
+    let mut vertices = outer.to_vec();
+    for hole in holes {
+        vertices.extend_from_slice(hole);
+    }
+    let mut mesh = FillMesh {
+        vertices,
+        indices: Vec::new(),
+    };
+
+    let mut rings = Rings::default();
+    let Some(mut start) = rings.link(outer, 0, true) else {
+        return mesh;
+    };
+    if rings.next(start) == rings.prev(start) {
+        return mesh;
+    }
+
+    let mut offset = outer.len();
+    let mut leftmost = Vec::new();
+    for hole in holes {
+        if let Some(ring) = rings.link(hole, offset, false) {
+            if ring == rings.next(ring) {
+                rings.nodes[ring].steiner = true;
+            }
+            leftmost.push(rings.leftmost(ring));
+        }
+        offset += hole.len();
+    }
+    leftmost.sort_by(|&a, &b| rings.nodes[a].x.total_cmp(&rings.nodes[b].x));
+    for hole in leftmost {
+        start = rings.eliminate_hole(hole, start);
+    }
+
+    rings.clip(Some(start), &mut mesh.indices, Pass::Plain);
+    mesh
+}
+
+/// Smallest angle one facet may turn through, bounding segment counts when
+/// tolerances are zero or tiny
+const MIN_FACET_ANGLE: f64 = 1e-3;
+
+/// Tolerances for faceting curves and curved surfaces
+///
+/// A facet is fine enough when it deviates from the true shape by at most
+/// `max_chord_error`, turns through at most `max_angle_deviation`, and is
+/// no longer than `max_edge_length`. Facets are never split below
+/// `min_edge_length`, whatever the other limits ask for.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TessellationSettings {
+    /// Maximum chord error (distance from curve to chord)
+    pub max_chord_error: f64,
+    /// Maximum angle between adjacent face normals (in radians)
+    pub max_angle_deviation: f64,
+    /// Minimum edge length
+    pub min_edge_length: f64,
+    /// Maximum edge length, unlimited when `None` so flat faces stay as
+    /// few triangles as possible
+    pub max_edge_length: Option<f64>,
+    /// Target number of triangles (soft limit)
+    pub target_triangle_count: Option<usize>,
+}
+
+impl Default for TessellationSettings {
+    fn default() -> Self {
+        Self {
+            max_chord_error: 0.01,
+            max_angle_deviation: 0.1, // ~5.7 degrees
+            min_edge_length: 0.001,
+            max_edge_length: None,
+            target_triangle_count: None,
+        }
+    }
+}
+
+impl TessellationSettings {
+    /// Settings for display at `pixel_size` drawing units per pixel
+    ///
+    /// The chord error becomes half a pixel, so curves stay smooth however
+    /// far the view is zoomed in and are not over-refined when zoomed out;
+    /// the angle limit is kept for smooth shading.
+    pub fn for_pixel_size(&self, pixel_size: f64) -> Self {
+        Self {
+            max_chord_error: 0.5 * pixel_size.abs().max(f64::EPSILON),
+            ..*self
+        }
+    }
+
+    /// Largest angle one segment of an arc of `radius` may subtend
+    pub fn arc_step(&self, radius: f64) -> f64 {
+        let radius = radius.abs();
+        let mut step = self.max_angle_deviation;
+        if self.max_chord_error < radius {
+            step = step.min(2.0 * (1.0 - self.max_chord_error / radius).acos());
+        }
+        if let Some(max_edge) = self.max_edge_length.filter(|&l| l < 2.0 * radius) {
+            step = step.min(2.0 * (max_edge / (2.0 * radius)).asin());
+        }
+        if self.min_edge_length > 0.0 && radius > 0.0 {
+            step = step.max(2.0 * (self.min_edge_length / (2.0 * radius)).min(1.0).asin());
+        }
+        step.max(MIN_FACET_ANGLE)
+    }
+
+    /// Number of segments approximating an arc of `radius` sweeping `sweep`
+    /// radians, at least one
+    pub fn arc_segments(&self, radius: f64, sweep: f64) -> usize {
+        ((sweep.abs() / self.arc_step(radius)).ceil() as usize).max(1)
+    }
+}
+
+// ============================================================================
+// Ear clipping over linked rings
+// ============================================================================
+
+#[derive(Debug, Clone)]
+struct Node {
+    /// Index into the mesh vertices
+    i: usize,
+    x: f64,
+    y: f64,
+    prev: usize,
+    next: usize,
+    /// Lone hole point, never filtered out
+    steiner: bool,
+}
+
+/// How hard to try before giving up on a loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pass {
+    Plain,
+    Filtered,
+    Cured,
+}
+
+/// Doubly linked loops of points in one arena; removed nodes stay in the
+/// arena but drop out of their loop
+#[derive(Debug, Default)]
+struct Rings {
+    nodes: Vec<Node>,
+}
+
+impl Rings {
+    fn next(&self, n: usize) -> usize {
+        self.nodes[n].next
+    }
+
+    fn prev(&self, n: usize) -> usize {
+        self.nodes[n].prev
+    }
+
+    fn point(&self, n: usize) -> Point2D {
+        Point2D::new(self.nodes[n].x, self.nodes[n].y)
+    }
+
+    fn insert(&mut self, i: usize, p: Point2D, after: Option<usize>) -> usize {
+        let id = self.nodes.len();
+        let (prev, next) = match after {
+            Some(last) => (last, self.nodes[last].next),
+            None => (id, id),
+        };
+        self.nodes.push(Node {
+            i,
+            x: p.x,
+            y: p.y,
+            prev,
+            next,
+            steiner: false,
+        });
+        if let Some(last) = after {
+            self.nodes[next].prev = id;
+            self.nodes[last].next = id;
+        }
+        id
+    }
+
+    fn remove(&mut self, n: usize) {
+        let (prev, next) = (self.nodes[n].prev, self.nodes[n].next);
+        self.nodes[next].prev = prev;
+        self.nodes[prev].next = next;
+    }
+
+    /// Link a loop counter-clockwise (or clockwise), dropping a repeated
+    /// closing point
+    fn link(&mut self, points: &[Point2D], offset: usize, ccw: bool) -> Option<usize> {
+        let area: f64 = (0..points.len())
+            .map(|k| points[k].cross(&points[(k + 1) % points.len()]))
+            .sum();
+        let mut last = None;
+        if ccw == (area > 0.0) {
+            for (k, p) in points.iter().enumerate() {
+                last = Some(self.insert(offset + k, *p, last));
+            }
+        } else {
+            for (k, p) in points.iter().enumerate().rev() {
+                last = Some(self.insert(offset + k, *p, last));
+            }
+        }
+
+        let last = last?;
+        if self.equals(last, self.next(last)) {
+            self.remove(last);
+            return Some(self.next(last));
+        }
+        Some(last)
+    }
+
+    fn equals(&self, a: usize, b: usize) -> bool {
+        self.nodes[a].x == self.nodes[b].x && self.nodes[a].y == self.nodes[b].y
+    }
+
+    /// Twice the signed area of `p, q, r`; negative for a left turn
+    fn area(&self, p: usize, q: usize, r: usize) -> f64 {
+        let (p, q, r) = (&self.nodes[p], &self.nodes[q], &self.nodes[r]);
+        (q.y - p.y) * (r.x - q.x) - (q.x - p.x) * (r.y - q.y)
+    }
+
+    /// Drop repeated and collinear points between `start` and `end`
+    fn filter(&mut self, start: usize, end: Option<usize>) -> usize {
+        let mut end = end.unwrap_or(start);
+        let mut p = start;
+        loop {
+            let (prev, next) = (self.prev(p), self.next(p));
+            if !self.nodes[p].steiner && (self.equals(p, next) || self.area(prev, p, next) == 0.0) {
+                self.remove(p);
+                p = prev;
+                end = prev;
+                if p == self.next(p) {
+                    break;
+                }
+                continue;
+            }
+            p = next;
+            if p == end {
+                break;
+            }
+        }
+        end
+    }
+
+    fn clip(&mut self, ear: Option<usize>, indices: &mut Vec<u32>, pass: Pass) {
+        let Some(mut ear) = ear else {
+            return;
+        };
+        let mut stop = ear;
+        while self.prev(ear) != self.next(ear) {
+            let (prev, next) = (self.prev(ear), self.next(ear));
+            if self.is_ear(ear) {
+                indices.extend(
+                    [self.nodes[prev].i, self.nodes[ear].i, self.nodes[next].i].map(|i| i as u32),
+                );
+                self.remove(ear);
+                ear = self.next(next);
+                stop = ear;
+                continue;
+            }
+
+            ear = next;
+            if ear == stop {
+                match pass {
+                    Pass::Plain => {
+                        let start = self.filter(ear, None);
+                        self.clip(Some(start), indices, Pass::Filtered);
+                    }
+                    Pass::Filtered => {
+                        let start = self.filter(ear, None);
+                        let start = self.cure_local_intersections(start, indices);
+                        self.clip(Some(start), indices, Pass::Cured);
+                    }
+                    Pass::Cured => self.split(ear, indices),
+                }
+                break;
+            }
+        }
+    }
+
+    /// Whether `ear` and its neighbours form a triangle with no other
+    /// point of the loop inside
+    fn is_ear(&self, ear: usize) -> bool {
+        let (a, c) = (self.prev(ear), self.next(ear));
+        if self.area(a, ear, c) >= 0.0 {
+            return false;
+        }
+
+        let (pa, pb, pc) = (self.point(a), self.point(ear), self.point(c));
+        let mut p = self.next(c);
+        while p != a {
+            if point_in_triangle(pa, pb, pc, self.point(p))
+                && self.area(self.prev(p), p, self.next(p)) >= 0.0
+            {
+                return false;
+            }
+            p = self.next(p);
+        }
+        true
+    }
+
+    /// Clip the triangles of small self-intersections, where an edge
+    /// doubles back across its neighbour
+    fn cure_local_intersections(&mut self, start: usize, indices: &mut Vec<u32>) -> usize {
+        let mut start = start;
+        let mut p = start;
+        loop {
+            let a = self.prev(p);
+            let b = self.next(self.next(p));
+            if !self.equals(a, b)
+                && self.intersects(a, p, self.next(p), b)
+                && self.locally_inside(a, b)
+                && self.locally_inside(b, a)
+            {
+                indices
+                    .extend([self.nodes[a].i, self.nodes[p].i, self.nodes[b].i].map(|i| i as u32));
+                let next = self.next(p);
+                self.remove(p);
+                self.remove(next);
+                p = b;
+                start = b;
+            }
+            p = self.next(p);
+            if p == start {
+                break;
+            }
+        }
+        self.filter(p, None)
+    }
+
+    /// Split the loop along a valid diagonal and clip both halves
+    fn split(&mut self, start: usize, indices: &mut Vec<u32>) {
+        let mut a = start;
+        loop {
+            let mut b = self.next(self.next(a));
+            while b != self.prev(a) {
+                if self.nodes[a].i != self.nodes[b].i && self.is_valid_diagonal(a, b) {
+                    let c = self.split_polygon(a, b);
+                    let a = self.filter(a, Some(self.next(a)));
+                    let c = self.filter(c, Some(self.next(c)));
+                    self.clip(Some(a), indices, Pass::Plain);
+                    self.clip(Some(c), indices, Pass::Plain);
+                    return;
+                }
+                b = self.next(b);
+            }
+            a = self.next(a);
+            if a == start {
+                return;
+            }
+        }
+    }
+
+    fn leftmost(&self, start: usize) -> usize {
+        let mut p = start;
+        let mut leftmost = start;
+        loop {
+            let (n, l) = (&self.nodes[p], &self.nodes[leftmost]);
+            if n.x < l.x || (n.x == l.x && n.y < l.y) {
+                leftmost = p;
+            }
+            p = self.next(p);
+            if p == start {
+                return leftmost;
+            }
+        }
+    }
+
+    /// Join a hole to the outer loop through a bridge edge
+    fn eliminate_hole(&mut self, hole: usize, outer: usize) -> usize {
+        let Some(bridge) = self.find_hole_bridge(hole, outer) else {
+            return outer;
+        };
+        let reverse = self.split_polygon(bridge, hole);
+        self.filter(reverse, Some(self.next(reverse)));
+        self.filter(bridge, Some(self.next(bridge)))
+    }
+
+    /// Outer vertex visible from the hole's leftmost point (David Eberly,
+    /// "Triangulation by Ear Clipping")
+    fn find_hole_bridge(&self, hole: usize, outer: usize) -> Option<usize> {
+        let (hx, hy) = (self.nodes[hole].x, self.nodes[hole].y);
+        let mut qx = f64::NEG_INFINITY;
+        let mut m = None;
+
+        // Nearest edge to the left of the hole point on a horizontal ray
+        let mut p = outer;
+        loop {
+            let (n, next) = (&self.nodes[p], &self.nodes[self.next(p)]);
+            if hy <= n.y && hy >= next.y && next.y != n.y {
+                let x = n.x + (hy - n.y) * (next.x - n.x) / (next.y - n.y);
+                if x <= hx && x > qx {
+                    qx = x;
+                    m = Some(if n.x < next.x { p } else { self.next(p) });
+                    if x == hx {
+                        // Touches the hole point: bridge straight to it
+                        return m;
+                    }
+                }
+            }
+            p = self.next(p);
+            if p == outer {
+                break;
+            }
+        }
+        let mut m = m?;
+
+        // A reflex vertex inside the triangle between the hole point, the
+        // ray hit and the edge end may block the view; take the one at the
+        // smallest angle to the ray instead
+        let stop = m;
+        let (mx, my) = (self.nodes[m].x, self.nodes[m].y);
+        let mut tan_min = f64::INFINITY;
+        let mut p = m;
+        loop {
+            let n = &self.nodes[p];
+            let (ax, cx) = if hy < my { (hx, qx) } else { (qx, hx) };
+            if hx >= n.x
+                && n.x >= mx
+                && hx != n.x
+                && point_in_triangle(
+                    Point2D::new(ax, hy),
+                    Point2D::new(mx, my),
+                    Point2D::new(cx, hy),
+                    Point2D::new(n.x, n.y),
+                )
+            {
+                let tan = (hy - n.y).abs() / (hx - n.x);
+                if self.locally_inside(p, hole)
+                    && (tan < tan_min
+                        || (tan == tan_min
+                            && (n.x > self.nodes[m].x
+                                || (n.x == self.nodes[m].x && self.sector_contains_sector(m, p)))))
+                {
+                    m = p;
+                    tan_min = tan;
+                }
+            }
+            p = self.next(p);
+            if p == stop {
+                return Some(m);
+            }
+        }
+    }
+
+    fn sector_contains_sector(&self, m: usize, p: usize) -> bool {
+        self.area(self.prev(m), m, self.prev(p)) < 0.0
+            && self.area(self.next(p), m, self.next(m)) < 0.0
+    }
+
+    fn is_valid_diagonal(&self, a: usize, b: usize) -> bool {
+        let (na, nb) = (&self.nodes[a], &self.nodes[b]);
+        if self.nodes[na.next].i == nb.i
+            || self.nodes[na.prev].i == nb.i
+            || self.intersects_polygon(a, b)
+        {
+            return false;
+        }
+        let visible = self.locally_inside(a, b)
+            && self.locally_inside(b, a)
+            && self.middle_inside(a, b)
+            && (self.area(na.prev, a, nb.prev) != 0.0 || self.area(a, nb.prev, b) != 0.0);
+        let zero_length = self.equals(a, b)
+            && self.area(na.prev, a, na.next) > 0.0
+            && self.area(nb.prev, b, nb.next) > 0.0;
+        visible || zero_length
+    }
+
+    /// Whether segments `p1 q1` and `p2 q2` intersect, touching included
+    fn intersects(&self, p1: usize, q1: usize, p2: usize, q2: usize) -> bool {
+        let o1 = sign(self.area(p1, q1, p2));
+        let o2 = sign(self.area(p1, q1, q2));
+        let o3 = sign(self.area(p2, q2, p1));
+        let o4 = sign(self.area(p2, q2, q1));
+
+        (o1 != o2 && o3 != o4)
+            || (o1 == 0 && self.on_segment(p1, p2, q1))
+            || (o2 == 0 && self.on_segment(p1, q2, q1))
+            || (o3 == 0 && self.on_segment(p2, p1, q2))
+            || (o4 == 0 && self.on_segment(p2, q1, q2))
+    }
+
+    /// For collinear `p, q, r`: whether `q` lies on segment `p r`
+    fn on_segment(&self, p: usize, q: usize, r: usize) -> bool {
+        let (p, q, r) = (&self.nodes[p], &self.nodes[q], &self.nodes[r]);
+        q.x <= p.x.max(r.x) && q.x >= p.x.min(r.x) && q.y <= p.y.max(r.y) && q.y >= p.y.min(r.y)
+    }
+
+    fn intersects_polygon(&self, a: usize, b: usize) -> bool {
+        let (ia, ib) = (self.nodes[a].i, self.nodes[b].i);
+        let mut p = a;
+        loop {
+            let next = self.next(p);
+            let (ip, inext) = (self.nodes[p].i, self.nodes[next].i);
+            if ip != ia && inext != ia && ip != ib && inext != ib && self.intersects(p, next, a, b)
+            {
+                return true;
+            }
+            p = next;
+            if p == a {
+                return false;
+            }
+        }
+    }
+
+    /// Whether the diagonal `a b` leaves `a` into the loop's interior
+    fn locally_inside(&self, a: usize, b: usize) -> bool {
+        let (prev, next) = (self.prev(a), self.next(a));
+        if self.area(prev, a, next) < 0.0 {
+            self.area(a, b, next) >= 0.0 && self.area(a, prev, b) >= 0.0
+        } else {
+            self.area(a, b, prev) < 0.0 || self.area(a, next, b) < 0.0
+        }
+    }
+
+    /// Whether the midpoint of `a b` is inside the loop
+    fn middle_inside(&self, a: usize, b: usize) -> bool {
+        let px = (self.nodes[a].x + self.nodes[b].x) / 2.0;
+        let py = (self.nodes[a].y + self.nodes[b].y) / 2.0;
+        let mut inside = false;
+        let mut p = a;
+        loop {
+            let (n, next) = (&self.nodes[p], &self.nodes[self.next(p)]);
+            if (n.y > py) != (next.y > py)
+                && next.y != n.y
+                && px < (next.x - n.x) * (py - n.y) / (next.y - n.y) + n.x
+            {
+                inside = !inside;
+            }
+            p = self.next(p);
+            if p == a {
+                return inside;
+            }
+        }
+    }
+
+    /// Connect `a` to `b` with a diagonal, splitting the loop in two;
+    /// returns the copy of `b` on the second loop
+    fn split_polygon(&mut self, a: usize, b: usize) -> usize {
+        let (pa, pb) = (self.point(a), self.point(b));
+        let (ia, ib) = (self.nodes[a].i, self.nodes[b].i);
+        let a2 = self.insert(ia, pa, None);
+        let b2 = self.insert(ib, pb, None);
+        let an = self.next(a);
+        let bp = self.prev(b);
+
+        self.nodes[a].next = b;
+        self.nodes[b].prev = a;
+        self.nodes[a2].next = an;
+        self.nodes[an].prev = a2;
+        self.nodes[b2].next = a2;
+        self.nodes[a2].prev = b2;
+        self.nodes[bp].next = b2;
+        self.nodes[b2].prev = bp;
+        b2
+    }
+}
+
+fn sign(value: f64) -> i8 {
+    if value > 0.0 {
+        1
+    } else if value < 0.0 {
+        -1
+    } else {
+        0
+    }
+}
+
+/// Whether `p` is inside or on triangle `a b c` (clockwise in the ring's
+/// area convention)
+fn point_in_triangle(a: Point2D, b: Point2D, c: Point2D, p: Point2D) -> bool {
+    (c.x - p.x) * (a.y - p.y) >= (a.x - p.x) * (c.y - p.y)
+        && (a.x - p.x) * (b.y - p.y) >= (b.x - p.x) * (a.y - p.y)
+        && (b.x - p.x) * (c.y - p.y) >= (c.x - p.x) * (b.y - p.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(points: &[(f64, f64)]) -> Vec<Point2D> {
+        points.iter().map(|&(x, y)| Point2D::new(x, y)).collect()
+    }
+
+    fn assert_ccw(mesh: &FillMesh) {
+        for [a, b, c] in mesh.triangles() {
+            assert!((b - a).cross(&(c - a)) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_concave_polygon() {
+        // U shape, clockwise with a repeated closing point
+        let outer = ring(&[
+            (0.0, 0.0),
+            (0.0, 3.0),
+            (1.0, 3.0),
+            (1.0, 1.0),
+            (2.0, 1.0),
+            (2.0, 3.0),
+            (3.0, 3.0),
+            (3.0, 0.0),
+            (0.0, 0.0),
+        ]);
+        let mesh = tessellate(&outer, &[]);
+
+        assert_eq!(mesh.triangle_count(), 6);
+        assert!((mesh.area() - 7.0).abs() < 1e-12);
+        assert_ccw(&mesh);
+        assert!(tessellate(&ring(&[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)]), &[]).is_empty());
+    }
+
+    #[test]
+    fn test_arc_segments() {
+        let settings = TessellationSettings {
+            max_angle_deviation: std::f64::consts::PI,
+            ..Default::default()
+        };
+        let full = 2.0 * std::f64::consts::PI;
+        // Chord error alone: the segment count grows with the square root
+        // of the radius
+        let small = settings.arc_segments(1.0, full);
+        let large = settings.arc_segments(100.0, full);
+        assert!(large > 9 * small && large < 11 * small);
+        let step = full / large as f64;
+        assert!(100.0 * (1.0 - (step / 2.0).cos()) <= settings.max_chord_error);
+
+        // Half a pixel on a zoomed-in view is finer than the document's
+        // tolerance, and the angle limit still applies when zoomed out
+        assert!(settings.for_pixel_size(0.001).arc_segments(100.0, full) > large);
+        let coarse = TessellationSettings::default().for_pixel_size(1000.0);
+        assert_eq!(coarse.arc_segments(100.0, full), (full / 0.1).ceil() as usize);
+        // Edge length limits cap straight-ish arcs, and nothing is split
+        // below the minimum edge length
+        let capped = TessellationSettings {
+            max_edge_length: Some(1.0),
+            ..coarse
+        };
+        assert!(capped.arc_segments(100.0, full) >= 628);
+        assert_eq!(TessellationSettings::default().arc_segments(1e-6, full), 2);
+    }
+
+    #[test]
+    fn test_holes() {
+        let outer = ring(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]);
+        let holes = vec![
+            ring(&[(1.0, 1.0), (4.0, 1.0), (4.0, 4.0), (1.0, 4.0)]),
+            ring(&[(6.0, 6.0), (6.0, 9.0), (9.0, 9.0), (9.0, 6.0)]),
+            // Touches the outer boundary at (10, 5)
+            ring(&[(7.0, 4.0), (10.0, 5.0), (7.0, 5.0)]),
+        ];
+        let polygon = Polygon2D::with_holes(outer, holes);
+        let mesh = FillMesh::from_polygon(&polygon);
+
+        assert_eq!(mesh.vertices.len(), 15);
+        assert!((mesh.area() - (100.0 - 9.0 - 9.0 - 1.5)).abs() < 1e-9);
+        assert_ccw(&mesh);
+        // Every point is used and no triangle covers a hole
+        for i in 0..mesh.vertices.len() as u32 {
+            assert!(mesh.indices.contains(&i));
+        }
+        for [a, b, c] in mesh.triangles() {
+            let centroid = (a + b + c) / 3.0;
+            assert!(polygon
+                .holes
+                .iter()
+                .all(|h| !Polygon2D::new(h.clone()).contains_point(&centroid)));
+        }
+    }
+}
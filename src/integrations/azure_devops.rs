@@ -46,6 +46,7 @@
 //! azure.initialize()?;
 //! ```
 
+use crate::enterprise::tracing::TracedSend;
 use super::{
     Annotation, AnnotationLevel, CheckResult, CheckStatus, CIIntegration, IntegrationError,
     Result, WebhookEvent,
@@ -249,7 +250,7 @@ impl AzureDevOpsIntegration {
             .basic_auth("", Some(&self.config.token))
             .header(header::CONTENT_TYPE, "application/json")
             .json(&status)
-            .send()
+            .send_traced("azure_devops.create_pr_status")
             .await?;
 
         if !response.status().is_success() {
@@ -323,7 +324,7 @@ impl AzureDevOpsIntegration {
             .basic_auth("", Some(&self.config.token))
             .header(header::CONTENT_TYPE, "application/json")
             .json(&thread)
-            .send()
+            .send_traced("azure_devops.create_pr_thread")
             .await?;
 
         if !response.status().is_success() {
@@ -366,7 +367,7 @@ impl AzureDevOpsIntegration {
             .basic_auth("", Some(&self.config.token))
             .header(header::CONTENT_TYPE, "application/json-patch+json")
             .json(&link)
-            .send()
+            .send_traced("azure_devops.link_work_item")
             .await?;
 
         if !response.status().is_success() {
@@ -413,7 +414,7 @@ impl AzureDevOpsIntegration {
             .basic_auth("", Some(&self.config.token))
             .header(header::CONTENT_TYPE, "application/json")
             .json(&test_run)
-            .send()
+            .send_traced("azure_devops.create_test_run")
             .await?;
 
         if !run_response.status().is_success() {
@@ -461,7 +462,7 @@ impl AzureDevOpsIntegration {
             .basic_auth("", Some(&self.config.token))
             .header(header::CONTENT_TYPE, "application/json")
             .json(&test_results)
-            .send()
+            .send_traced("azure_devops.add_test_results")
             .await?;
 
         if !results_response.status().is_success() {
@@ -491,7 +492,7 @@ impl AzureDevOpsIntegration {
             .basic_auth("", Some(&self.config.token))
             .header(header::CONTENT_TYPE, "application/json")
             .json(&complete_run)
-            .send()
+            .send_traced("azure_devops.complete_test_run")
             .await?;
 
         if !complete_response.status().is_success() {
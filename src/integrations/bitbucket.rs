@@ -46,6 +46,7 @@
 //! bitbucket.initialize()?;
 //! ```
 
+use crate::enterprise::tracing::TracedSend;
 use super::{
     Annotation, AnnotationLevel, CheckResult, CheckStatus, CIIntegration, IntegrationError,
     Result, WebhookEvent,
@@ -238,7 +239,7 @@ impl BitbucketIntegration {
             .basic_auth(&self.config.username, Some(&self.config.token))
             .header(header::CONTENT_TYPE, "application/json")
             .json(&build_status)
-            .send()
+            .send_traced("bitbucket.create_build_status")
             .await?;
 
         if !response.status().is_success() {
@@ -298,7 +299,7 @@ impl BitbucketIntegration {
             .basic_auth(&self.config.username, Some(&self.config.token))
             .header(header::CONTENT_TYPE, "application/json")
             .json(&pr_comment)
-            .send()
+            .send_traced("bitbucket.post_pr_comment")
             .await?;
 
         if !response.status().is_success() {
@@ -389,7 +390,7 @@ impl BitbucketIntegration {
             .basic_auth(&self.config.username, Some(&self.config.token))
             .header(header::CONTENT_TYPE, "application/json")
             .json(&report)
-            .send()
+            .send_traced("bitbucket.create_code_insights_report")
             .await?;
 
         if !response.status().is_success() {
@@ -466,7 +467,7 @@ impl BitbucketIntegration {
                 .basic_auth(&self.config.username, Some(&self.config.token))
                 .header(header::CONTENT_TYPE, "application/json")
                 .json(chunk)
-                .send()
+                .send_traced("bitbucket.create_code_insights_annotations")
                 .await?;
 
             if !response.status().is_success() {
@@ -38,6 +38,7 @@
 //! github.initialize()?;
 //! ```
 
+use crate::enterprise::tracing::TracedSend;
 use super::{
     Annotation, AnnotationLevel, CheckResult, CheckStatus, CIIntegration, IntegrationConfig,
     IntegrationError, Result, WebhookEvent,
@@ -246,7 +247,7 @@ impl GitHubIntegration {
             .header(header::AUTHORIZATION, format!("Bearer {}", jwt))
             .header(header::ACCEPT, "application/vnd.github+json")
             .header("X-GitHub-Api-Version", GITHUB_API_VERSION)
-            .send()
+            .send_traced("github.get_access_token")
             .await?;
 
         if !response.status().is_success() {
@@ -348,7 +349,7 @@ impl GitHubIntegration {
             .header(header::ACCEPT, "application/vnd.github+json")
             .header("X-GitHub-Api-Version", GITHUB_API_VERSION)
             .json(&check_run)
-            .send()
+            .send_traced("github.create_check_run")
             .await?;
 
         if !response.status().is_success() {
@@ -404,7 +405,7 @@ impl GitHubIntegration {
             .header(header::ACCEPT, "application/vnd.github+json")
             .header("X-GitHub-Api-Version", GITHUB_API_VERSION)
             .json(&status_request)
-            .send()
+            .send_traced("github.create_commit_status")
             .await?;
 
         if !response.status().is_success() {
@@ -34,6 +34,7 @@
 //! gitlab.initialize()?;
 //! ```
 
+use crate::enterprise::tracing::TracedSend;
 use super::{
     Annotation, AnnotationLevel, CheckResult, CheckStatus, CIIntegration, IntegrationError,
     Result, WebhookEvent,
@@ -203,7 +204,7 @@ impl GitLabIntegration {
             .header(header::AUTHORIZATION, format!("Bearer {}", self.config.token))
             .header(header::CONTENT_TYPE, "application/json")
             .json(&status_request)
-            .send()
+            .send_traced("gitlab.create_commit_status")
             .await?;
 
         if !response.status().is_success() {
@@ -243,7 +244,7 @@ impl GitLabIntegration {
             .header(header::AUTHORIZATION, format!("Bearer {}", self.config.token))
             .header(header::CONTENT_TYPE, "application/json")
             .json(&note)
-            .send()
+            .send_traced("gitlab.post_merge_request_comment")
             .await?;
 
         if !response.status().is_success() {
@@ -307,7 +308,7 @@ impl GitLabIntegration {
             .header(header::AUTHORIZATION, format!("Bearer {}", self.config.token))
             .header(header::CONTENT_TYPE, "application/json")
             .json(&discussion)
-            .send()
+            .send_traced("gitlab.create_inline_comment")
             .await?;
 
         if !response.status().is_success() {
@@ -47,6 +47,7 @@
 //! jenkins.initialize()?;
 //! ```
 
+use crate::enterprise::tracing::TracedSend;
 use super::{
     Annotation, AnnotationLevel, CheckResult, CheckStatus, CIIntegration, IntegrationError,
     Result, WebhookEvent,
@@ -508,7 +509,7 @@ impl JenkinsIntegration {
             .post(&url)
             .basic_auth(&self.config.user, Some(&self.config.token))
             .json(&status_request)
-            .send()
+            .send_traced("jenkins.update_build_status")
             .await?;
 
         if !response.status().is_success() {
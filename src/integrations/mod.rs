@@ -15,6 +15,7 @@
 //! - Status reporting and commit annotations
 //! - Pipeline integration
 //! - Webhook handlers
+//! - Outbound calls traced as children of the triggering request
 //! - CLI tool for standalone scanning
 //!
 //! ## Usage
@@ -54,6 +54,7 @@ pub enum FileFormat {
     DWG,
     STEP,
     IGES,
+    ThreeMf,
     STL,
     OBJ,
     GLTF,
@@ -71,6 +72,7 @@ impl FileFormat {
             "dwg" => FileFormat::DWG,
             "step" | "stp" => FileFormat::STEP,
             "iges" | "igs" => FileFormat::IGES,
+            "3mf" => FileFormat::ThreeMf,
             "stl" => FileFormat::STL,
             "obj" => FileFormat::OBJ,
             "gltf" | "glb" => FileFormat::GLTF,
@@ -89,6 +91,7 @@ impl FileFormat {
             FileFormat::DWG => "dwg",
             FileFormat::STEP => "step",
             FileFormat::IGES => "iges",
+            FileFormat::ThreeMf => "3mf",
             FileFormat::STL => "stl",
             FileFormat::OBJ => "obj",
             FileFormat::GLTF => "gltf",
@@ -384,7 +387,7 @@ impl BatchConverter {
         }
 
         // Load input file
-        let _doc = match self.load_document(input_path, input_format) {
+        let doc = match self.load_document(input_path, input_format) {
             Ok(doc) => doc,
             Err(e) => {
                 let duration = start_time.elapsed().as_millis() as u64;
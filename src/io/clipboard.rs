@@ -0,0 +1,579 @@
+// CADDY - Enterprise CAD System
+// File I/O System - Clipboard Module
+
+//! Cross-document copy and paste
+//!
+//! A [`ClipboardContent`] is a self-contained snapshot of a selection: the
+//! entities themselves, the layer definitions they sit on and every block
+//! definition their inserts reference (nested blocks included). Because the
+//! definitions travel with the entities, pasting into another document
+//! reproduces the original colors, line types and line weights even when
+//! the target has never heard of those layers.
+//!
+//! When the target already has a layer or block of the same name but a
+//! different definition, [`ConflictPolicy`] decides who wins: `Rename`
+//! brings the source definition in under a fresh name so the pasted
+//! entities look exactly as they did, `UseTarget` keeps the target's
+//! definition and lets the pasted entities adopt it.
+//!
+//! Every paste allocates new entity ids, so the same content can be pasted
+//! any number of times. [`transfer`] is the drag-and-drop variant: paste
+//! into the target, then remove the originals from the source.
+//!
+//! ## Example
+//!
+//! ```
+//! use caddy::io::clipboard::{ClipboardContent, PasteOptions};
+//! use caddy::io::document::{Document, Entity, GeometryType, Line, Vec3};
+//!
+//! let mut source = Document::new();
+//! let id = source.add_entity(Entity::new(
+//!     GeometryType::Line(Line { start: Vec3::zero(), end: Vec3::new(10.0, 0.0, 0.0) }),
+//!     "0".to_string(),
+//! ));
+//!
+//! let content = ClipboardContent::copy(&source, &[id]).unwrap();
+//! let mut target = Document::new();
+//! let report = content
+//!     .paste_into(&mut target, &PasteOptions::at(Vec3::new(5.0, 5.0, 0.0)))
+//!     .unwrap();
+//! assert_eq!(report.entities.len(), 1);
+//! ```
+
+use crate::io::document::{Block, Document, Entity, GeometryType, Layer, Vec3};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Clipboard errors
+#[derive(Error, Debug)]
+pub enum ClipboardError {
+    #[error("Entity not found: {0}")]
+    EntityNotFound(Uuid),
+    #[error("Nothing selected to copy")]
+    EmptySelection,
+    #[error("Source and target are the same document")]
+    SameDocument,
+    #[error("Clipboard data error: {0}")]
+    Format(#[from] serde_json::Error),
+}
+
+pub type ClipboardResult<T> = Result<T, ClipboardError>;
+
+/// What to do when a pasted layer or block name already exists in the
+/// target with a different definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ConflictPolicy {
+    /// Bring the source definition in under a new name
+    #[default]
+    Rename,
+    /// Keep the target definition
+    UseTarget,
+}
+
+/// Paste options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasteOptions {
+    /// Where the content's base point lands; `None` pastes at the original
+    /// coordinates
+    pub position: Option<Vec3>,
+    /// Layer name conflicts
+    pub layers: ConflictPolicy,
+    /// Block name conflicts
+    pub blocks: ConflictPolicy,
+}
+
+impl Default for PasteOptions {
+    fn default() -> Self {
+        Self {
+            position: None,
+            layers: ConflictPolicy::Rename,
+            blocks: ConflictPolicy::Rename,
+        }
+    }
+}
+
+impl PasteOptions {
+    /// Paste with the base point at `position`
+    pub fn at(position: Vec3) -> Self {
+        Self {
+            position: Some(position),
+            ..Self::default()
+        }
+    }
+
+    /// Set the layer conflict policy
+    pub fn with_layers(mut self, policy: ConflictPolicy) -> Self {
+        self.layers = policy;
+        self
+    }
+
+    /// Set the block conflict policy
+    pub fn with_blocks(mut self, policy: ConflictPolicy) -> Self {
+        self.blocks = policy;
+        self
+    }
+}
+
+/// Outcome of a paste
+#[derive(Debug, Clone, Default)]
+pub struct PasteReport {
+    /// Ids of the new entities, in paste order
+    pub entities: Vec<Uuid>,
+    /// Layers created in the target
+    pub layers_added: Vec<String>,
+    /// Blocks created in the target
+    pub blocks_added: Vec<String>,
+    /// Source names that were renamed on the way in, as (from, to)
+    pub renamed: Vec<(String, String)>,
+}
+
+/// A copied selection with everything needed to recreate it elsewhere
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardContent {
+    /// Document the content was copied from
+    pub source: Uuid,
+    /// Reference point used when pasting at a position
+    pub base_point: Vec3,
+    /// Copied entities, in draw order
+    pub entities: Vec<Entity>,
+    /// Definitions of every layer the entities use
+    pub layers: Vec<Layer>,
+    /// Definitions of every block the entities insert, nested ones first
+    pub blocks: Vec<Block>,
+}
+
+impl ClipboardContent {
+    /// Copy entities out of `doc`; the base point is the lower-left corner
+    /// of the selection
+    pub fn copy(doc: &Document, ids: &[Uuid]) -> ClipboardResult<Self> {
+        if ids.is_empty() {
+            return Err(ClipboardError::EmptySelection);
+        }
+        if let Some(missing) = ids.iter().find(|id| doc.get_entity(**id).is_none()) {
+            return Err(ClipboardError::EntityNotFound(*missing));
+        }
+
+        // Keep draw order rather than selection order
+        let wanted: HashSet<Uuid> = ids.iter().copied().collect();
+        let entities: Vec<Entity> = doc
+            .entities
+            .iter()
+            .filter(|entity| wanted.contains(&entity.id))
+            .cloned()
+            .collect();
+
+        let mut block_names = Vec::new();
+        for entity in &entities {
+            collect_blocks(doc, entity, &mut block_names);
+        }
+        let blocks: Vec<Block> = block_names
+            .iter()
+            .filter_map(|name| doc.get_block(name).cloned())
+            .collect();
+
+        let mut layer_names: Vec<&str> = Vec::new();
+        for entity in entities.iter().chain(blocks.iter().flat_map(|b| &b.entities)) {
+            if !layer_names.contains(&entity.layer.as_str()) {
+                layer_names.push(&entity.layer);
+            }
+        }
+        let layers = layer_names
+            .iter()
+            .filter_map(|name| doc.get_layer(name).cloned())
+            .collect();
+
+        let bbox = entities
+            .iter()
+            .skip(1)
+            .fold(entities[0].bounding_box(), |bbox, e| bbox.union(&e.bounding_box()));
+
+        Ok(Self {
+            source: doc.id,
+            base_point: bbox.min,
+            entities,
+            layers,
+            blocks,
+        })
+    }
+
+    /// Serialize for the system clipboard
+    pub fn to_json(&self) -> ClipboardResult<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Read content placed on the system clipboard by [`Self::to_json`]
+    pub fn from_json(data: &str) -> ClipboardResult<Self> {
+        Ok(serde_json::from_str(data)?)
+    }
+
+    /// Paste into `target`
+    pub fn paste_into(&self, target: &mut Document, options: &PasteOptions) -> ClipboardResult<PasteReport> {
+        let mut report = PasteReport::default();
+
+        let mut layer_map = HashMap::new();
+        for layer in &self.layers {
+            let name = match target.get_layer(&layer.name) {
+                None => layer.name.clone(),
+                Some(existing) if same_layer(existing, layer) => continue,
+                Some(_) if options.layers == ConflictPolicy::UseTarget => continue,
+                Some(_) => {
+                    let (name, exists) = variant(&layer.name, |n| {
+                        target.get_layer(n).map(|existing| same_layer(existing, layer))
+                    });
+                    report.renamed.push((layer.name.clone(), name.clone()));
+                    layer_map.insert(layer.name.clone(), name.clone());
+                    if exists {
+                        continue;
+                    }
+                    name
+                }
+            };
+            report.layers_added.push(name.clone());
+            target.add_layer(Layer { name, ..layer.clone() });
+        }
+
+        // Nested blocks come first, so by the time a block is compared the
+        // inserts inside it already point at their final names
+        let mut block_map = HashMap::new();
+        for block in &self.blocks {
+            let incoming = Block {
+                entities: block
+                    .entities
+                    .iter()
+                    .map(|entity| remap(entity, &layer_map, &block_map, None))
+                    .collect(),
+                ..block.clone()
+            };
+            let name = match target.get_block(&block.name) {
+                None => block.name.clone(),
+                Some(existing) if same_block(existing, &incoming) => continue,
+                Some(_) if options.blocks == ConflictPolicy::UseTarget => continue,
+                Some(_) => {
+                    let (name, exists) = variant(&block.name, |n| {
+                        target.get_block(n).map(|existing| same_block(existing, &incoming))
+                    });
+                    report.renamed.push((block.name.clone(), name.clone()));
+                    block_map.insert(block.name.clone(), name.clone());
+                    if exists {
+                        continue;
+                    }
+                    name
+                }
+            };
+            report.blocks_added.push(name.clone());
+            target.add_block(Block { name, ..incoming });
+        }
+
+        let offset = options.position.map(|p| {
+            Vec3::new(
+                p.x - self.base_point.x,
+                p.y - self.base_point.y,
+                p.z - self.base_point.z,
+            )
+        });
+        let mut pasted: Vec<Entity> = self
+            .entities
+            .iter()
+            .map(|entity| remap(entity, &layer_map, &block_map, offset))
+            .collect();
+        // Associative hatches follow the pasted copies of their boundaries;
+        // boundaries left behind are dropped from the association
+        let ids: HashMap<Uuid, Uuid> = self.entities.iter().zip(&pasted).map(|(old, new)| (old.id, new.id)).collect();
+        for entity in &mut pasted {
+            if let GeometryType::Hatch(hatch) = &mut entity.geometry {
+                hatch.associated = hatch.associated.iter().filter_map(|id| ids.get(id).copied()).collect();
+            }
+        }
+        for entity in pasted {
+            report.entities.push(target.add_entity(entity));
+        }
+
+        Ok(report)
+    }
+}
+
+/// Move entities from `source` into `target`, as when dragging a selection
+/// between documents
+pub fn transfer(
+    source: &mut Document,
+    target: &mut Document,
+    ids: &[Uuid],
+    options: &PasteOptions,
+) -> ClipboardResult<PasteReport> {
+    if source.id == target.id {
+        return Err(ClipboardError::SameDocument);
+    }
+    let content = ClipboardContent::copy(source, ids)?;
+    let report = content.paste_into(target, options)?;
+    for id in ids {
+        source.remove_entity(*id);
+    }
+    Ok(report)
+}
+
+/// Add the blocks `entity` inserts, depth first so nested blocks come
+/// before the blocks that use them
+fn collect_blocks(doc: &Document, entity: &Entity, names: &mut Vec<String>) {
+    let GeometryType::Insert(insert) = &entity.geometry else {
+        return;
+    };
+    if names.contains(&insert.block_name) {
+        return;
+    }
+    if let Some(block) = doc.get_block(&insert.block_name) {
+        // Mark before recursing so a self-referencing block terminates
+        names.push(insert.block_name.clone());
+        let index = names.len() - 1;
+        for nested in &block.entities {
+            collect_blocks(doc, nested, names);
+        }
+        let name = names.remove(index);
+        names.push(name);
+    }
+}
+
+/// Copy an entity under a new id with renamed references, optionally moved
+fn remap(
+    entity: &Entity,
+    layers: &HashMap<String, String>,
+    blocks: &HashMap<String, String>,
+    offset: Option<Vec3>,
+) -> Entity {
+    let mut entity = entity.clone();
+    entity.id = Uuid::new_v4();
+    if let Some(name) = layers.get(&entity.layer) {
+        entity.layer = name.clone();
+    }
+    if let GeometryType::Insert(insert) = &mut entity.geometry {
+        if let Some(name) = blocks.get(&insert.block_name) {
+            insert.block_name = name.clone();
+        }
+    }
+    if let Some(offset) = offset {
+        translate(&mut entity.geometry, offset);
+    }
+    entity
+}
+
+fn translate(geometry: &mut GeometryType, d: Vec3) {
+    let shift = |p: &mut Vec3| {
+        p.x += d.x;
+        p.y += d.y;
+        p.z += d.z;
+    };
+    match geometry {
+        GeometryType::Point(point) => shift(&mut point.position),
+        GeometryType::Line(line) => {
+            shift(&mut line.start);
+            shift(&mut line.end);
+        }
+        GeometryType::Circle(circle) => shift(&mut circle.center),
+        GeometryType::Arc(arc) => shift(&mut arc.center),
+        GeometryType::Ellipse(ellipse) => shift(&mut ellipse.center),
+        GeometryType::Polyline(polyline) => {
+            polyline.vertices.iter_mut().for_each(|v| shift(&mut v.position));
+        }
+        GeometryType::Spline(spline) => spline.control_points.iter_mut().for_each(shift),
+        GeometryType::Text(text) => shift(&mut text.position),
+        GeometryType::MText(mtext) => shift(&mut mtext.position),
+        GeometryType::Dimension(dim) => {
+            use crate::io::document::DimensionType;
+            shift(&mut dim.definition_point);
+            shift(&mut dim.text_position);
+            match &mut dim.dim_type {
+                DimensionType::Linear { start, end, .. } | DimensionType::Aligned { start, end } => {
+                    shift(start);
+                    shift(end);
+                }
+                DimensionType::Angular { center, start, end } => {
+                    shift(center);
+                    shift(start);
+                    shift(end);
+                }
+                DimensionType::Radial { center, .. } | DimensionType::Diameter { center, .. } => {
+                    shift(center)
+                }
+            }
+        }
+        GeometryType::Insert(insert) => shift(&mut insert.position),
+        GeometryType::Hatch(hatch) => {
+            hatch.boundaries.iter_mut().flatten().for_each(shift);
+        }
+        GeometryType::PointCloud(cloud) => cloud.translate([d.x, d.y, d.z]),
+    }
+}
+
+/// Layers look the same if everything that affects display and plotting
+/// matches; visibility and lock state are per-document working state
+fn same_layer(a: &Layer, b: &Layer) -> bool {
+    a.color == b.color
+        && a.line_type == b.line_type
+        && a.line_weight == b.line_weight
+        && a.plottable == b.plottable
+}
+
+/// Blocks match if their content does, regardless of entity ids
+fn same_block(a: &Block, b: &Block) -> bool {
+    fn signature(block: &Block) -> Option<serde_json::Value> {
+        let mut block = block.clone();
+        block.entities.iter_mut().for_each(|e| e.id = Uuid::nil());
+        block.description.clear();
+        serde_json::to_value(block).ok()
+    }
+    signature(a).is_some() && signature(a) == signature(b)
+}
+
+/// First of `name (2)`, `name (3)`, ... that is either free or already
+/// holds a matching definition from an earlier paste; `lookup` reports
+/// whether a name is taken and, if so, whether its definition matches
+fn variant(name: &str, lookup: impl Fn(&str) -> Option<bool>) -> (String, bool) {
+    (2..)
+        .map(|n| format!("{name} ({n})"))
+        .find_map(|candidate| match lookup(&candidate) {
+            None => Some((candidate, false)),
+            Some(true) => Some((candidate, true)),
+            Some(false) => None,
+        })
+        .expect("unbounded name search")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{Color, Insert, Line, LineType, LineWeight};
+
+    fn layer(name: &str, color: Color) -> Layer {
+        Layer {
+            name: name.to_string(),
+            color,
+            line_type: LineType::Continuous,
+            line_weight: LineWeight::Default,
+            visible: true,
+            locked: false,
+            frozen: false,
+            plottable: true,
+            confidential: false,
+        }
+    }
+
+    fn line(layer: &str, x: f64) -> Entity {
+        Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::new(x, 0.0, 0.0),
+                end: Vec3::new(x + 1.0, 1.0, 0.0),
+            }),
+            layer.to_string(),
+        )
+    }
+
+    fn insert(block: &str) -> Entity {
+        Entity::new(
+            GeometryType::Insert(Insert {
+                block_name: block.to_string(),
+                position: Vec3::new(4.0, 4.0, 0.0),
+                scale: Vec3::new(1.0, 1.0, 1.0),
+                rotation: 0.0,
+                attributes: HashMap::new(),
+            }),
+            "0".to_string(),
+        )
+    }
+
+    fn source() -> (Document, Vec<Uuid>) {
+        let mut doc = Document::new();
+        doc.add_layer(layer("Walls", Color::red()));
+        doc.add_block(Block {
+            name: "Bolt".to_string(),
+            base_point: Vec3::zero(),
+            entities: vec![line("Walls", 0.0)],
+            description: String::new(),
+        });
+        doc.add_block(Block {
+            name: "Plate".to_string(),
+            base_point: Vec3::zero(),
+            entities: vec![insert("Bolt")],
+            description: String::new(),
+        });
+        let mut styled = line("Walls", 2.0);
+        styled.color = Some(Color::blue());
+        let ids = vec![doc.add_entity(styled), doc.add_entity(insert("Plate"))];
+        doc.add_entity(line("0", 9.0));
+        (doc, ids)
+    }
+
+    #[test]
+    fn test_copy_carries_definitions() {
+        let (doc, ids) = source();
+        let content = ClipboardContent::copy(&doc, &ids).unwrap();
+
+        assert_eq!(content.entities.len(), 2);
+        let blocks: Vec<_> = content.blocks.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(blocks, ["Bolt", "Plate"]);
+        assert!(content.layers.iter().any(|l| l.name == "Walls"));
+        assert_eq!(content.base_point.x, 2.0);
+
+        let restored = ClipboardContent::from_json(&content.to_json().unwrap()).unwrap();
+        assert_eq!(restored.entities[0].id, content.entities[0].id);
+        assert!(matches!(
+            ClipboardContent::copy(&doc, &[Uuid::new_v4()]),
+            Err(ClipboardError::EntityNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_paste_preserves_styles_and_renames_conflicts() {
+        let (doc, ids) = source();
+        let content = ClipboardContent::copy(&doc, &ids).unwrap();
+
+        let mut target = Document::new();
+        target.add_layer(layer("Walls", Color::green()));
+        let report = content
+            .paste_into(&mut target, &PasteOptions::at(Vec3::new(12.0, 0.0, 0.0)))
+            .unwrap();
+
+        assert_eq!(report.renamed, [("Walls".to_string(), "Walls (2)".to_string())]);
+        assert_eq!(target.get_layer("Walls (2)").unwrap().color, Color::red());
+        assert_eq!(target.get_layer("Walls").unwrap().color, Color::green());
+
+        let pasted = target.get_entity(report.entities[0]).unwrap();
+        assert_ne!(pasted.id, ids[0]);
+        assert_eq!(pasted.layer, "Walls (2)");
+        assert_eq!(pasted.color, Some(Color::blue()));
+        let GeometryType::Line(l) = &pasted.geometry else { panic!() };
+        assert_eq!(l.start.x, 12.0);
+        assert_eq!(target.get_block("Bolt").unwrap().entities[0].layer, "Walls (2)");
+
+        // Same definitions second time round: nothing new
+        let again = content.paste_into(&mut target, &PasteOptions::default()).unwrap();
+        assert!(again.blocks_added.is_empty());
+        assert_eq!(target.entities.len(), 4);
+
+        let mut kept = Document::new();
+        kept.add_layer(layer("Walls", Color::green()));
+        let report = content
+            .paste_into(&mut kept, &PasteOptions::default().with_layers(ConflictPolicy::UseTarget))
+            .unwrap();
+        assert!(report.renamed.is_empty());
+        assert_eq!(kept.get_entity(report.entities[0]).unwrap().layer, "Walls");
+    }
+
+    #[test]
+    fn test_transfer_moves_entities() {
+        let (mut doc, ids) = source();
+        let mut target = Document::new();
+        let report = transfer(&mut doc, &mut target, &ids[..1], &PasteOptions::default()).unwrap();
+
+        assert_eq!(report.entities.len(), 1);
+        assert!(doc.get_entity(ids[0]).is_none());
+        assert_eq!(target.entities.len(), 1);
+
+        let copy = doc.clone();
+        let mut same = copy;
+        assert!(matches!(
+            transfer(&mut doc, &mut same, &ids[1..], &PasteOptions::default()),
+            Err(ClipboardError::SameDocument)
+        ));
+    }
+}
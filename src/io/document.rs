@@ -2,6 +2,18 @@
 // File I/O System - Document Structure Module
 // Agent 6 - File I/O System Developer
 
+use crate::core::primitives::{BoundingBox3, Point3, Ray3};
+use crate::geometry::hatch::{active_loops, hatch_lines, HatchPattern, HatchStyle};
+use crate::geometry::line::LineSegment2D;
+use crate::geometry::point::Point2D;
+use crate::geometry::pointcloud::PointCloud;
+use crate::geometry::spatial::{box_distance, ray_entry, SpatialIndex};
+use crate::geometry::tessellate::TessellationSettings;
+use crate::io::groups::{self, Group, NamedSelection};
+use crate::io::parameters::Parameters;
+use crate::io::tags::EntityQuery;
+use crate::io::readout::ReadoutFormat;
+use crate::io::regional::{Region, RegionalSettings};
 use crate::io::units::{Unit, PrecisionSettings};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -18,6 +30,12 @@ pub struct Document {
     /// Document settings
     pub settings: DocumentSettings,
     /// All entities in the document
+    ///
+    /// Prefer the entity methods, which keep the spatial index in sync.
+    /// Adding or removing entities here is detected and the index rebuilt on
+    /// the next [`sync_spatial_index`](Self::sync_spatial_index); after
+    /// replacing, reordering or reshaping entities in place, call
+    /// [`touch_entity`](Self::touch_entity) for the same effect.
     pub entities: Vec<Entity>,
     /// Layer definitions
     pub layers: HashMap<String, Layer>,
@@ -27,6 +45,56 @@ pub struct Document {
     pub views: HashMap<String, View>,
     /// Variables (custom properties)
     pub variables: HashMap<String, String>,
+    /// Named parameters driving constraints, array counts and fields
+    #[serde(default)]
+    pub parameters: Parameters,
+    /// Saved entity queries by name, for selection and visibility filters
+    #[serde(default)]
+    pub queries: HashMap<String, EntityQuery>,
+    /// Entity groups by name, picked as a unit
+    #[serde(default)]
+    pub groups: HashMap<String, Group>,
+    /// Saved selections by name
+    #[serde(default)]
+    pub selections: HashMap<String, NamedSelection>,
+    /// When entities and block definitions last changed
+    #[serde(default)]
+    revisions: Revisions,
+    /// Spatial index over entity bounds, rebuilt after loading
+    #[serde(skip)]
+    spatial: EntityIndex,
+}
+
+/// Spatial index over a document's entities
+#[derive(Debug, Clone, Default)]
+struct EntityIndex {
+    tree: SpatialIndex<Uuid>,
+    /// Position of each entity in `Document::entities`
+    positions: HashMap<Uuid, usize>,
+    /// Bumped by every entity change, see [`Document::touch_entity`]
+    generation: u64,
+    /// Generation the index reflects; the entity methods keep it equal to
+    /// `generation`, anything else leaves it behind
+    indexed: u64,
+    /// Entities handed out mutably since the last sync
+    dirty: HashSet<Uuid>,
+}
+
+/// Versions at which a document and its parts last changed
+///
+/// Every entity added, removed or handed out mutably, every hatch
+/// regenerated and every layer or block definition added or touched bumps
+/// the document version, so caches of derived data such as thumbnails can
+/// tell what is out of date. Writing to `Document::entities`,
+/// `Document::layers` or `Document::blocks` directly bypasses this.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Revisions {
+    /// Increases with every change
+    pub version: u64,
+    /// Version at which each entity last changed, including removed ones
+    pub entities: HashMap<Uuid, u64>,
+    /// Version at which each block definition last changed
+    pub blocks: HashMap<String, u64>,
 }
 
 impl Document {
@@ -44,6 +112,7 @@ impl Document {
                 locked: false,
                 frozen: false,
                 plottable: true,
+                confidential: false,
             },
         );
 
@@ -56,33 +125,350 @@ impl Document {
             blocks: HashMap::new(),
             views: HashMap::new(),
             variables: HashMap::new(),
+            parameters: Parameters::new(),
+            queries: HashMap::new(),
+            groups: HashMap::new(),
+            selections: HashMap::new(),
+            revisions: Revisions::default(),
+            spatial: EntityIndex::default(),
+        }
+    }
+
+    /// Empty document set up with a region's units, paper and standards
+    pub fn for_region(region: Region) -> Self {
+        let mut doc = Self::new();
+        region.preset().apply(&mut doc.settings);
+        doc
+    }
+
+    /// New document starting from a template's settings, layers, blocks,
+    /// views, variables, parameters and queries, but none of its entities
+    /// or the groups and selections made of them
+    pub fn from_template(template: &Document) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            metadata: DocumentMetadata::default(),
+            groups: HashMap::new(),
+            selections: HashMap::new(),
+            revisions: Revisions::default(),
+            ..template.without_entities()
+        }
+    }
+
+    /// Copy of the document with everything but its entities
+    pub fn without_entities(&self) -> Self {
+        Self {
+            id: self.id,
+            metadata: self.metadata.clone(),
+            settings: self.settings.clone(),
+            entities: Vec::new(),
+            layers: self.layers.clone(),
+            blocks: self.blocks.clone(),
+            views: self.views.clone(),
+            variables: self.variables.clone(),
+            parameters: self.parameters.clone(),
+            queries: self.queries.clone(),
+            groups: self.groups.clone(),
+            selections: self.selections.clone(),
+            revisions: self.revisions.clone(),
+            spatial: EntityIndex::default(),
         }
     }
 
     /// Add an entity to the document
     pub fn add_entity(&mut self, entity: Entity) -> Uuid {
         let id = entity.id;
+        // Duplicate ids can't be keyed; they leave the index stale
+        let indexed = self.spatial_is_current() && !self.spatial.positions.contains_key(&id);
+        if indexed {
+            self.spatial.positions.insert(id, self.entities.len());
+            if let Some(bounds) = index_bounds(&entity) {
+                self.spatial.tree.insert(id, bounds);
+            }
+        }
         self.entities.push(entity);
+        self.touch_entity(id);
+        if indexed {
+            self.spatial.indexed = self.spatial.generation;
+        }
         id
     }
 
-    /// Remove an entity by ID
+    /// Insert an entity at a position in draw order, or at the end if
+    /// `index` is past it, with the same bookkeeping as
+    /// [`add_entity`](Self::add_entity)
+    pub fn insert_entity(&mut self, index: usize, entity: Entity) -> Uuid {
+        if index >= self.entities.len() {
+            return self.add_entity(entity);
+        }
+        let id = entity.id;
+        let indexed = self.spatial_is_current() && !self.spatial.positions.contains_key(&id);
+        if indexed {
+            for shifted in &self.entities[index..] {
+                if let Some(pos) = self.spatial.positions.get_mut(&shifted.id) {
+                    *pos += 1;
+                }
+            }
+            self.spatial.positions.insert(id, index);
+            if let Some(bounds) = index_bounds(&entity) {
+                self.spatial.tree.insert(id, bounds);
+            }
+        }
+        self.entities.insert(index, entity);
+        self.touch_entity(id);
+        if indexed {
+            self.spatial.indexed = self.spatial.generation;
+        }
+        id
+    }
+
+    /// Remove an entity by ID, also dropping it from groups and saved
+    /// selections
     pub fn remove_entity(&mut self, id: Uuid) -> Option<Entity> {
-        if let Some(pos) = self.entities.iter().position(|e| e.id == id) {
-            Some(self.entities.remove(pos))
-        } else {
-            None
+        let pos = self.entity_position(id)?;
+        let indexed = self.spatial_is_current();
+        let entity = self.entities.remove(pos);
+        self.touch_entity(id);
+        groups::forget_entity(self, id);
+        if indexed {
+            self.spatial.positions.remove(&id);
+            self.spatial.tree.remove(&id);
+            self.spatial.dirty.remove(&id);
+            for (i, shifted) in self.entities.iter().enumerate().skip(pos) {
+                self.spatial.positions.insert(shifted.id, i);
+            }
+            self.spatial.indexed = self.spatial.generation;
         }
+        Some(entity)
     }
 
     /// Get an entity by ID
     pub fn get_entity(&self, id: Uuid) -> Option<&Entity> {
-        self.entities.iter().find(|e| e.id == id)
+        self.entity_position(id).map(|pos| &self.entities[pos])
     }
 
     /// Get a mutable reference to an entity by ID
+    ///
+    /// The entity's bounds are re-indexed on the next
+    /// [`sync_spatial_index`](Self::sync_spatial_index); queries before then
+    /// check it directly. The entity counts as changed from then on.
     pub fn get_entity_mut(&mut self, id: Uuid) -> Option<&mut Entity> {
-        self.entities.iter_mut().find(|e| e.id == id)
+        let pos = self.entity_position(id)?;
+        let indexed = self.spatial_is_current();
+        self.touch_entity(id);
+        if indexed {
+            self.spatial.dirty.insert(id);
+            self.spatial.indexed = self.spatial.generation;
+        }
+        Some(&mut self.entities[pos])
+    }
+
+    /// Current document version, see [`Revisions`]
+    pub fn version(&self) -> u64 {
+        self.revisions.version
+    }
+
+    /// When entities and block definitions last changed
+    pub fn revisions(&self) -> &Revisions {
+        &self.revisions
+    }
+
+    /// Version at which an entity last changed, `None` if it never did
+    /// since the document was created or loaded without revisions
+    pub fn entity_revision(&self, id: Uuid) -> Option<u64> {
+        self.revisions.entities.get(&id).copied()
+    }
+
+    /// Version at which a block definition last changed, as for
+    /// [`entity_revision`](Self::entity_revision)
+    pub fn block_revision(&self, name: &str) -> Option<u64> {
+        self.revisions.blocks.get(name).copied()
+    }
+
+    /// Record a change to an entity made behind the document's back
+    ///
+    /// Also marks the spatial index stale, so queries scan the entities
+    /// until the next [`sync_spatial_index`](Self::sync_spatial_index).
+    pub fn touch_entity(&mut self, id: Uuid) {
+        self.spatial.generation += 1;
+        self.revisions.version += 1;
+        self.revisions.entities.insert(id, self.revisions.version);
+    }
+
+    /// Record a change to a block definition made behind the document's back
+    pub fn touch_block(&mut self, name: &str) {
+        self.revisions.version += 1;
+        self.revisions.blocks.insert(name.to_string(), self.revisions.version);
+    }
+
+    fn entity_position(&self, id: Uuid) -> Option<usize> {
+        match self.spatial.positions.get(&id) {
+            Some(&pos) if self.entities.get(pos).is_some_and(|e| e.id == id) => Some(pos),
+            _ => self.entities.iter().position(|e| e.id == id),
+        }
+    }
+
+    /// Whether the spatial index covers exactly the current entities
+    ///
+    /// Entities changed since the index was last brought up to date bump
+    /// its generation; the count catches entities pushed or removed through
+    /// [`Document::entities`] without [`touch_entity`](Self::touch_entity).
+    fn spatial_is_current(&self) -> bool {
+        self.spatial.indexed == self.spatial.generation && self.spatial.positions.len() == self.entities.len()
+    }
+
+    /// Rebuild the spatial index from scratch
+    ///
+    /// Needed after changing entity geometry through [`Document::entities`]
+    /// directly. Documents with duplicate entity ids can't be indexed and
+    /// fall back to linear scans.
+    pub fn rebuild_spatial_index(&mut self) {
+        let generation = self.spatial.generation;
+        let positions: HashMap<Uuid, usize> = self
+            .entities
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.id, i))
+            .collect();
+        self.spatial = if positions.len() == self.entities.len() {
+            EntityIndex {
+                tree: SpatialIndex::from_items(
+                    self.entities.iter().filter_map(|e| Some((e.id, index_bounds(e)?))),
+                ),
+                positions,
+                generation,
+                indexed: generation,
+                dirty: HashSet::new(),
+            }
+        } else {
+            EntityIndex { generation, ..EntityIndex::default() }
+        };
+    }
+
+    /// Bring the spatial index up to date with entity changes
+    ///
+    /// Re-indexes entities handed out by [`get_entity_mut`](Self::get_entity_mut),
+    /// or rebuilds if entities were added or removed behind the document's back.
+    /// Queries are correct without syncing, just slower.
+    ///
+    /// Associative hatches whose boundary entities changed are regenerated
+    /// first, so they are indexed with their new boundaries.
+    pub fn sync_spatial_index(&mut self) {
+        if !self.spatial_is_current() {
+            self.regenerate_hatches();
+            self.rebuild_spatial_index();
+            return;
+        }
+        let mut dirty = std::mem::take(&mut self.spatial.dirty);
+        dirty.extend(self.regenerate_hatches_following(Some(&dirty)));
+        for id in dirty {
+            let bounds = self.spatial.positions.get(&id).and_then(|&pos| index_bounds(&self.entities[pos]));
+            match bounds {
+                Some(bounds) => self.spatial.tree.insert(id, bounds),
+                None => {
+                    self.spatial.tree.remove(&id);
+                }
+            }
+        }
+        self.spatial.indexed = self.spatial.generation;
+    }
+
+    /// The spatial index, keyed by entity ID
+    ///
+    /// Only reflects the entities after [`sync_spatial_index`](Self::sync_spatial_index).
+    pub fn spatial_index(&self) -> &SpatialIndex<Uuid> {
+        &self.spatial.tree
+    }
+
+    /// Entities whose bounds intersect `region`
+    pub fn entities_in_box(&self, region: &BoundingBox) -> Vec<&Entity> {
+        let region = region.to_box3();
+        self.spatial_query(|tree| tree.query_box(&region), |bounds| bounds.intersects(&region))
+    }
+
+    /// Entities whose bounds come within `tolerance` of `point`
+    pub fn entities_near(&self, point: Vec3, tolerance: f64) -> Vec<&Entity> {
+        let point = point.to_point3();
+        self.spatial_query(
+            |tree| tree.query_point(&point, tolerance),
+            |bounds| box_distance(bounds, &point) <= tolerance,
+        )
+    }
+
+    /// Entity whose bounds are closest to `point`, within `max_distance`
+    pub fn nearest_entity(&self, point: Vec3, max_distance: f64) -> Option<(&Entity, f64)> {
+        let point = point.to_point3();
+        let distance = |e: &Entity| index_bounds(e).map(|b| box_distance(&b, &point));
+        if !self.spatial_is_current() {
+            return nearest(self.entities.iter(), distance, max_distance);
+        }
+        let indexed = self
+            .spatial
+            .tree
+            .nearest_by(&point, max_distance, |id, bounds| {
+                (!self.spatial.dirty.contains(&id)).then(|| box_distance(bounds, &point))
+            })
+            .and_then(|(id, d)| Some((self.get_entity(id)?, d)));
+        let dirty = nearest(self.dirty_entities(), distance, max_distance);
+        match (indexed, dirty) {
+            (Some(a), Some(b)) => Some(if b.1 < a.1 { b } else { a }),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Entities whose bounds a ray from `origin` along `direction` enters
+    /// within `max_distance`, nearest first, with the entry distance
+    pub fn entities_on_ray(&self, origin: Vec3, direction: Vec3, max_distance: f64) -> Vec<(&Entity, f64)> {
+        let ray = Ray3::new(
+            origin.to_point3(),
+            nalgebra::Vector3::new(direction.x, direction.y, direction.z),
+        );
+        let entry = |e: &Entity| {
+            let t = ray_entry(&ray, &index_bounds(e)?)?;
+            (t <= max_distance).then_some(t)
+        };
+        let mut hits: Vec<(&Entity, f64)> = if self.spatial_is_current() {
+            self.spatial
+                .tree
+                .query_ray(&ray, max_distance)
+                .into_iter()
+                .filter(|(id, _)| !self.spatial.dirty.contains(id))
+                .filter_map(|(id, t)| Some((self.get_entity(id)?, t)))
+                .chain(self.dirty_entities().filter_map(|e| Some((e, entry(e)?))))
+                .collect()
+        } else {
+            self.entities.iter().filter_map(|e| Some((e, entry(e)?))).collect()
+        };
+        hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+        hits
+    }
+
+    /// Run an index query, or test every entity's bounds when the index is
+    /// stale. Results are in document order either way.
+    fn spatial_query(
+        &self,
+        query: impl FnOnce(&SpatialIndex<Uuid>) -> Vec<Uuid>,
+        matches: impl Fn(&BoundingBox3) -> bool,
+    ) -> Vec<&Entity> {
+        let test = |e: &&Entity| index_bounds(e).is_some_and(|b| matches(&b));
+        if !self.spatial_is_current() {
+            return self.entities.iter().filter(test).collect();
+        }
+        let mut positions: Vec<usize> = query(&self.spatial.tree)
+            .into_iter()
+            .filter(|id| !self.spatial.dirty.contains(id))
+            .chain(self.dirty_entities().filter(test).map(|e| e.id))
+            .filter_map(|id| self.spatial.positions.get(&id).copied())
+            .collect();
+        positions.sort_unstable();
+        positions.into_iter().map(|pos| &self.entities[pos]).collect()
+    }
+
+    fn dirty_entities(&self) -> impl Iterator<Item = &Entity> {
+        self.spatial
+            .dirty
+            .iter()
+            .filter_map(|id| self.spatial.positions.get(id).map(|&pos| &self.entities[pos]))
     }
 
     /// Get all entities on a specific layer
@@ -96,6 +482,7 @@ impl Document {
     /// Add a layer
     pub fn add_layer(&mut self, layer: Layer) {
         self.layers.insert(layer.name.clone(), layer);
+        self.revisions.version += 1;
     }
 
     /// Get a layer by name
@@ -105,6 +492,7 @@ impl Document {
 
     /// Add a block definition
     pub fn add_block(&mut self, block: Block) {
+        self.touch_block(&block.name);
         self.blocks.insert(block.name.clone(), block);
     }
 
@@ -113,6 +501,16 @@ impl Document {
         self.blocks.get(name)
     }
 
+    /// Get a mutable reference to a block definition, which counts as
+    /// changed from then on
+    pub fn get_block_mut(&mut self, name: &str) -> Option<&mut Block> {
+        if !self.blocks.contains_key(name) {
+            return None;
+        }
+        self.touch_block(name);
+        self.blocks.get_mut(name)
+    }
+
     /// Calculate bounding box of all entities
     pub fn bounding_box(&self) -> Option<BoundingBox> {
         if self.entities.is_empty() {
@@ -142,6 +540,81 @@ impl Document {
         counts
     }
 
+    /// Add a hatch whose boundaries follow other entities
+    ///
+    /// `boundary_ids` name closed entities (circles, ellipses, closed
+    /// polylines); the first is taken as the outer loop. The hatch's
+    /// boundaries are rebuilt from them whenever they change, see
+    /// [`sync_spatial_index`](Self::sync_spatial_index). Returns `None`
+    /// without adding anything if none of them is a closed entity.
+    pub fn add_associative_hatch(&mut self, mut hatch: Hatch, boundary_ids: &[Uuid], layer: String) -> Option<Uuid> {
+        hatch.associated = boundary_ids.to_vec();
+        if !self.refresh_hatch(&mut hatch) {
+            return None;
+        }
+        Some(self.add_entity(Entity::new(GeometryType::Hatch(hatch), layer)))
+    }
+
+    /// Rebuild every associative hatch from its boundary entities
+    ///
+    /// Returns the number of hatches regenerated.
+    pub fn regenerate_hatches(&mut self) -> usize {
+        self.regenerate_hatches_following(None).len()
+    }
+
+    /// Regenerate the associative hatches following any of `changed`, or
+    /// all of them; returns their ids
+    fn regenerate_hatches_following(&mut self, changed: Option<&HashSet<Uuid>>) -> Vec<Uuid> {
+        let stale: Vec<usize> = self
+            .entities
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| match &e.geometry {
+                GeometryType::Hatch(h) => {
+                    h.is_associative() && changed.is_none_or(|c| h.associated.iter().any(|id| c.contains(id)))
+                }
+                _ => false,
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        let mut regenerated = Vec::new();
+        for i in stale {
+            let GeometryType::Hatch(mut hatch) = self.entities[i].geometry.clone() else {
+                continue;
+            };
+            self.refresh_hatch(&mut hatch);
+            self.entities[i].geometry = GeometryType::Hatch(hatch);
+            let id = self.entities[i].id;
+            self.touch_entity(id);
+            regenerated.push(id);
+        }
+        regenerated
+    }
+
+    /// Rebuild a hatch's boundaries from the entities it follows
+    ///
+    /// Entities that are gone or no longer closed are dropped from the
+    /// association; once none are left the hatch keeps its last boundaries
+    /// as a plain hatch. Returns whether any boundary was found.
+    fn refresh_hatch(&self, hatch: &mut Hatch) -> bool {
+        let mut loops = Vec::new();
+        hatch.associated.retain(|id| {
+            match self.get_entity(*id).and_then(|e| boundary_loop(&e.geometry)) {
+                Some(boundary) => {
+                    loops.push(boundary);
+                    true
+                }
+                None => false,
+            }
+        });
+        if loops.is_empty() {
+            return false;
+        }
+        hatch.boundaries = loops;
+        true
+    }
+
     /// Validate document integrity
     pub fn validate(&self) -> Vec<String> {
         let mut errors = Vec::new();
@@ -173,6 +646,79 @@ impl Default for Document {
     }
 }
 
+/// Segments per full turn when flattening boundary curves
+const BOUNDARY_SEGMENTS: usize = 64;
+
+/// Outline of a closed entity, for use as a hatch boundary
+pub fn boundary_loop(geometry: &GeometryType) -> Option<Vec<Vec3>> {
+    let ellipse = |center: Vec3, a: f64, b: f64, rotation: f64| {
+        let (sin_r, cos_r) = rotation.sin_cos();
+        (0..BOUNDARY_SEGMENTS)
+            .map(|i| {
+                let t = i as f64 / BOUNDARY_SEGMENTS as f64 * std::f64::consts::TAU;
+                let (x, y) = (a * t.cos(), b * t.sin());
+                Vec3::new(center.x + x * cos_r - y * sin_r, center.y + x * sin_r + y * cos_r, center.z)
+            })
+            .collect()
+    };
+
+    match geometry {
+        GeometryType::Circle(c) if c.radius > 0.0 => Some(ellipse(c.center, c.radius, c.radius, 0.0)),
+        GeometryType::Ellipse(e) if e.major_axis > 0.0 && e.minor_axis > 0.0 => {
+            Some(ellipse(e.center, e.major_axis, e.minor_axis, e.rotation))
+        }
+        GeometryType::Polyline(p) if p.closed && p.vertices.len() > 2 => {
+            let mut points = Vec::new();
+            for (i, v) in p.vertices.iter().enumerate() {
+                let next = p.vertices[(i + 1) % p.vertices.len()].position;
+                points.push(v.position);
+                if v.bulge == 0.0 {
+                    continue;
+                }
+                // Bulge is tan(sweep / 4), positive counter-clockwise
+                let sweep = 4.0 * v.bulge.atan();
+                let (start, end) = (
+                    Point2D::new(v.position.x, v.position.y),
+                    Point2D::new(next.x, next.y),
+                );
+                let chord = end - start;
+                let sagitta_ratio = (1.0 - v.bulge * v.bulge) / (4.0 * v.bulge);
+                let center = start.midpoint(&end) + Point2D::new(-chord.y, chord.x) * sagitta_ratio;
+                let steps = ((sweep.abs() / std::f64::consts::TAU * BOUNDARY_SEGMENTS as f64).ceil() as usize).max(2);
+                for k in 1..steps {
+                    let p = (start - center).rotate(sweep * k as f64 / steps as f64) + center;
+                    points.push(Vec3::new(p.x, p.y, v.position.z));
+                }
+            }
+            Some(points)
+        }
+        _ => None,
+    }
+}
+
+/// Bounds an entity is indexed under; entities without finite bounds aren't
+fn index_bounds(entity: &Entity) -> Option<BoundingBox3> {
+    let bounds = entity.bounding_box();
+    let finite = [bounds.min, bounds.max]
+        .iter()
+        .all(|v| v.x.is_finite() && v.y.is_finite() && v.z.is_finite());
+    finite.then(|| bounds.to_box3())
+}
+
+fn nearest<'a>(
+    entities: impl Iterator<Item = &'a Entity>,
+    distance: impl Fn(&Entity) -> Option<f64>,
+    max_distance: f64,
+) -> Option<(&'a Entity, f64)> {
+    entities
+        .filter_map(|e| Some((e, distance(e)?)))
+        .filter(|(_, d)| *d <= max_distance)
+        .fold(None, |best: Option<(&Entity, f64)>, (e, d)| match best {
+            Some((_, b)) if b <= d => best,
+            _ => Some((e, d)),
+        })
+}
+
 /// Document metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentMetadata {
@@ -226,6 +772,9 @@ pub struct DocumentSettings {
     pub units: Unit,
     /// Precision settings
     pub precision: PrecisionSettings,
+    /// Coordinate, distance and angle readout
+    #[serde(default)]
+    pub readout: ReadoutFormat,
     /// Paper space settings
     pub paper_size: PaperSize,
     /// Background color
@@ -234,6 +783,12 @@ pub struct DocumentSettings {
     pub grid: GridSettings,
     /// Snap settings
     pub snap: SnapSettings,
+    /// Tolerances curved geometry is faceted to for display and export
+    #[serde(default)]
+    pub tessellation: TessellationSettings,
+    /// Regional dimension standard, plot paper and date format
+    #[serde(default)]
+    pub regional: RegionalSettings,
     /// Automatic save interval (seconds), None = disabled
     pub autosave_interval: Option<u64>,
     /// Create backup on save
@@ -245,10 +800,13 @@ impl Default for DocumentSettings {
         Self {
             units: Unit::Millimeters,
             precision: PrecisionSettings::default(),
+            readout: ReadoutFormat::default(),
             paper_size: PaperSize::A4,
             background_color: Color::new(0, 0, 0),
             grid: GridSettings::default(),
             snap: SnapSettings::default(),
+            tessellation: TessellationSettings::default(),
+            regional: RegionalSettings::default(),
             autosave_interval: Some(300), // 5 minutes
             create_backup: true,
         }
@@ -299,6 +857,19 @@ pub struct GridSettings {
     pub color: Color,
     /// Snap to grid
     pub snap: bool,
+    /// Grid layout
+    #[serde(default)]
+    pub grid_type: GridType,
+    /// Active isoplane for isometric drafting
+    #[serde(default)]
+    pub isoplane: Isoplane,
+    /// Coarsen or refine spacing as the view zooms
+    #[serde(default = "default_adaptive")]
+    pub adaptive: bool,
+}
+
+fn default_adaptive() -> bool {
+    true
 }
 
 impl Default for GridSettings {
@@ -309,6 +880,65 @@ impl Default for GridSettings {
             minor_spacing: 1.0,
             color: Color::new(128, 128, 128),
             snap: false,
+            grid_type: GridType::Rectangular,
+            isoplane: Isoplane::Top,
+            adaptive: true,
+        }
+    }
+}
+
+/// Grid layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GridType {
+    /// Standard rectangular grid
+    #[default]
+    Rectangular,
+    /// Polar grid (concentric circles and radial lines)
+    Polar,
+    /// Isometric grid (lines at 30°, 90°, and 150°)
+    Isometric,
+}
+
+/// Isometric drawing plane
+///
+/// Each plane is drawn with two of the three isometric axes; ortho and grid
+/// snap follow the active plane's axes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Isoplane {
+    /// Left face: 90° and 150° axes
+    Left,
+    /// Top face: 30° and 150° axes
+    #[default]
+    Top,
+    /// Right face: 30° and 90° axes
+    Right,
+}
+
+impl Isoplane {
+    /// Next plane in the Left → Top → Right cycle
+    pub fn next(self) -> Self {
+        match self {
+            Isoplane::Left => Isoplane::Top,
+            Isoplane::Top => Isoplane::Right,
+            Isoplane::Right => Isoplane::Left,
+        }
+    }
+
+    /// Display name
+    pub fn name(self) -> &'static str {
+        match self {
+            Isoplane::Left => "Left",
+            Isoplane::Top => "Top",
+            Isoplane::Right => "Right",
+        }
+    }
+
+    /// The plane's two drawing axes, in degrees
+    pub fn axes(self) -> [f64; 2] {
+        match self {
+            Isoplane::Left => [90.0, 150.0],
+            Isoplane::Top => [30.0, 150.0],
+            Isoplane::Right => [30.0, 90.0],
         }
     }
 }
@@ -332,6 +962,22 @@ pub struct SnapSettings {
     pub tangent: bool,
     /// Snap distance threshold
     pub threshold: f64,
+    /// Ortho mode
+    #[serde(default)]
+    pub ortho: bool,
+    /// Polar tracking
+    #[serde(default)]
+    pub polar: bool,
+    /// Polar tracking increment (degrees)
+    #[serde(default = "default_polar_increment")]
+    pub polar_increment: f64,
+    /// Polar snap distance along tracking vectors (0 = off)
+    #[serde(default)]
+    pub polar_distance: f64,
+}
+
+fn default_polar_increment() -> f64 {
+    45.0
 }
 
 impl Default for SnapSettings {
@@ -345,6 +991,10 @@ impl Default for SnapSettings {
             perpendicular: true,
             tangent: true,
             threshold: 5.0, // pixels
+            ortho: false,
+            polar: false,
+            polar_increment: default_polar_increment(),
+            polar_distance: 0.0,
         }
     }
 }
@@ -406,6 +1056,7 @@ pub enum GeometryType {
     Dimension(Dimension),
     Insert(Insert),
     Hatch(Hatch),
+    PointCloud(PointCloud),
 }
 
 impl GeometryType {
@@ -424,6 +1075,7 @@ impl GeometryType {
             GeometryType::Dimension(_) => "Dimension",
             GeometryType::Insert(_) => "Insert",
             GeometryType::Hatch(_) => "Hatch",
+            GeometryType::PointCloud(_) => "PointCloud",
         }
     }
 
@@ -441,7 +1093,13 @@ impl GeometryType {
             GeometryType::MText(t) => BoundingBox::from_point(t.position),
             GeometryType::Dimension(d) => d.bounding_box(),
             GeometryType::Insert(i) => BoundingBox::from_point(i.position),
-            GeometryType::Hatch(_) => BoundingBox::invalid(),
+            GeometryType::Hatch(h) => BoundingBox::from_points(&h.boundaries.concat()),
+            GeometryType::PointCloud(c) => c.bounds().map_or_else(BoundingBox::invalid, |b| {
+                BoundingBox::new(
+                    Vec3::new(b.min.x, b.min.y, b.min.z),
+                    Vec3::new(b.max.x, b.max.y, b.max.z),
+                )
+            }),
         }
     }
 }
@@ -605,6 +1263,80 @@ pub struct Hatch {
     pub scale: f64,
     pub angle: f64,
     pub boundaries: Vec<Vec<Vec3>>,
+    /// Pattern lines for patterns that aren't built in, as read from a
+    /// `.pat` file
+    #[serde(default)]
+    pub definition: Option<HatchPattern>,
+    /// Which nested boundary loops (islands) the hatch respects
+    #[serde(default)]
+    pub style: HatchStyle,
+    /// Entities the boundaries follow; empty for a non-associative hatch
+    #[serde(default)]
+    pub associated: Vec<Uuid>,
+}
+
+impl Hatch {
+    /// Non-associative hatch with a named pattern at scale 1
+    pub fn new(pattern: impl Into<String>, boundaries: Vec<Vec<Vec3>>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            scale: 1.0,
+            angle: 0.0,
+            boundaries,
+            definition: None,
+            style: HatchStyle::Normal,
+            associated: Vec::new(),
+        }
+    }
+
+    /// Hatch using a pattern definition, e.g. one read from a `.pat` file
+    pub fn with_definition(pattern: HatchPattern, boundaries: Vec<Vec<Vec3>>) -> Self {
+        Self {
+            definition: Some(pattern.clone()),
+            ..Self::new(pattern.name, boundaries)
+        }
+    }
+
+    /// Whether the hatch is a solid fill rather than lines
+    pub fn is_solid(&self) -> bool {
+        self.pattern.eq_ignore_ascii_case("SOLID")
+    }
+
+    /// Whether the boundaries follow other entities
+    pub fn is_associative(&self) -> bool {
+        !self.associated.is_empty()
+    }
+
+    /// Pattern lines to draw; unknown built-in names draw as ANSI31, as in
+    /// SVG export
+    pub fn pattern_definition(&self) -> HatchPattern {
+        self.definition
+            .clone()
+            .unwrap_or_else(|| HatchPattern::builtin_or_default(&self.pattern))
+    }
+
+    fn loops(&self) -> Vec<Vec<Point2D>> {
+        self.boundaries
+            .iter()
+            .map(|b| b.iter().map(|p| Point2D::new(p.x, p.y)).collect())
+            .collect()
+    }
+
+    /// Boundaries the hatch clips against under its island style
+    pub fn active_boundaries(&self) -> Vec<&Vec<Vec3>> {
+        active_loops(&self.loops(), self.style)
+            .into_iter()
+            .map(|i| &self.boundaries[i])
+            .collect()
+    }
+
+    /// Pattern lines clipped to the boundaries; empty for solid fills
+    pub fn lines(&self) -> Vec<LineSegment2D> {
+        if self.is_solid() {
+            return Vec::new();
+        }
+        hatch_lines(&self.pattern_definition(), &self.loops(), self.scale, self.angle, self.style)
+    }
 }
 
 /// Layer definition
@@ -626,6 +1358,9 @@ pub struct Layer {
     pub frozen: bool,
     /// Plottable
     pub plottable: bool,
+    /// Withheld from redacted exports
+    #[serde(default)]
+    pub confidential: bool,
 }
 
 /// Block definition (reusable component)
@@ -726,6 +1461,31 @@ pub enum LineType {
     Custom { name: String, pattern: Vec<f64> },
 }
 
+impl LineType {
+    /// Line type name (`CONTINUOUS`, `DASHED`, ... or the custom name)
+    pub fn name(&self) -> &str {
+        match self {
+            LineType::Continuous => "CONTINUOUS",
+            LineType::Dashed => "DASHED",
+            LineType::Dotted => "DOTTED",
+            LineType::DashDot => "DASHDOT",
+            LineType::Custom { name, .. } => name,
+        }
+    }
+
+    /// Dash pattern in drawing units: positive values are dashes, negative
+    /// values are gaps and zero is a dot. Empty for continuous lines.
+    pub fn pattern(&self) -> &[f64] {
+        match self {
+            LineType::Continuous => &[],
+            LineType::Dashed => &[0.5, -0.25],
+            LineType::Dotted => &[0.0, -0.25],
+            LineType::DashDot => &[0.5, -0.25, 0.0, -0.25],
+            LineType::Custom { pattern, .. } => pattern,
+        }
+    }
+}
+
 /// Line weight
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LineWeight {
@@ -764,6 +1524,10 @@ impl Vec3 {
     pub fn unit_z() -> Self {
         Self::new(0.0, 0.0, 1.0)
     }
+
+    pub fn to_point3(&self) -> Point3 {
+        Point3::new(self.x, self.y, self.z)
+    }
 }
 
 /// Axis-aligned bounding box
@@ -837,4 +1601,137 @@ impl BoundingBox {
             self.max.z - self.min.z,
         )
     }
+
+    pub fn to_box3(&self) -> BoundingBox3 {
+        BoundingBox3::new(self.min.to_point3(), self.max.to_point3())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(x: f64, y: f64) -> Entity {
+        Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::new(x, y, 0.0),
+                end: Vec3::new(x + 1.0, y + 1.0, 0.0),
+            }),
+            "0".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_spatial_index_follows_entity_changes() {
+        let mut doc = Document::new();
+        let ids: Vec<Uuid> = (0..100)
+            .map(|i| doc.add_entity(line((i % 10) as f64 * 5.0, (i / 10) as f64 * 5.0)))
+            .collect();
+        assert_eq!(doc.spatial_index().len(), 100);
+
+        let region = BoundingBox::new(Vec3::new(9.0, 9.0, -1.0), Vec3::new(16.0, 11.0, 1.0));
+        let found: Vec<Uuid> = doc.entities_in_box(&region).iter().map(|e| e.id).collect();
+        assert_eq!(found, vec![ids[22], ids[23]]);
+
+        // Edits through get_entity_mut are seen before and after syncing
+        if let Some(GeometryType::Line(l)) = doc.get_entity_mut(ids[0]).map(|e| &mut e.geometry) {
+            l.start = Vec3::new(12.0, 10.0, 0.0);
+            l.end = Vec3::new(12.5, 10.5, 0.0);
+        }
+        assert_eq!(doc.entities_in_box(&region).len(), 3);
+        doc.sync_spatial_index();
+        assert_eq!(doc.entities_in_box(&region)[0].id, ids[0]);
+
+        doc.remove_entity(ids[22]);
+        assert_eq!(doc.get_entity(ids[99]).map(|e| e.id), Some(ids[99]));
+        assert_eq!(doc.entities_in_box(&region).len(), 2);
+
+        let (nearest, distance) = doc.nearest_entity(Vec3::new(47.0, 47.0, 0.0), 10.0).unwrap();
+        assert_eq!(nearest.id, ids[99]);
+        assert!((distance - 2.0_f64.sqrt()).abs() < 1e-12);
+        assert!(doc.nearest_entity(Vec3::new(100.0, 100.0, 0.0), 10.0).is_none());
+
+        let hits = doc.entities_on_ray(Vec3::new(-1.0, 0.5, 0.0), Vec3::unit_x(), 12.0);
+        let hit_ids: Vec<Uuid> = hits.iter().map(|(e, _)| e.id).collect();
+        assert_eq!(hit_ids, vec![ids[1], ids[2]]);
+
+        // Direct writes to `entities` leave the index stale: queries fall back
+        // to scanning until the next sync rebuilds it
+        doc.entities.push(line(13.0, 9.5));
+        assert_eq!(doc.entities_in_box(&region).len(), 3);
+        doc.sync_spatial_index();
+        assert_eq!(doc.spatial_index().len(), 100);
+        assert_eq!(doc.entities_in_box(&region).len(), 3);
+
+        // Entities replaced or reordered in place are flagged by touching them
+        let pos = doc.entities.iter().position(|e| e.id == ids[23]).unwrap();
+        doc.entities[pos] = line(40.0, 40.0);
+        doc.entities.swap(0, 1);
+        let replaced = doc.entities[pos].id;
+        doc.touch_entity(replaced);
+        let found: Vec<Uuid> = doc.entities_in_box(&region).iter().map(|e| e.id).collect();
+        assert_eq!(found, vec![ids[0], doc.entities[99].id]);
+        assert!(doc.get_entity_mut(ids[0]).is_some());
+        assert_eq!(doc.entities_in_box(&region).len(), 2);
+        doc.sync_spatial_index();
+        assert_eq!(doc.entities_in_box(&region).len(), 2);
+
+        let json = serde_json::to_string(&doc).unwrap();
+        let mut loaded: Document = serde_json::from_str(&json).unwrap();
+        assert!(loaded.spatial_index().is_empty());
+        assert_eq!(loaded.entities_in_box(&region).len(), 2);
+        loaded.sync_spatial_index();
+        assert_eq!(loaded.spatial_index().len(), 100);
+    }
+
+    #[test]
+    fn test_associative_hatch_follows_boundaries() {
+        let mut doc = Document::new();
+        let corners = [(0.0, 0.0), (20.0, 0.0), (20.0, 20.0), (0.0, 20.0)];
+        let outer = doc.add_entity(Entity::new(
+            GeometryType::Polyline(Polyline {
+                vertices: corners
+                    .iter()
+                    .map(|&(x, y)| Vertex { position: Vec3::new(x, y, 0.0), bulge: 0.0 })
+                    .collect(),
+                closed: true,
+            }),
+            "0".to_string(),
+        ));
+        let island = doc.add_entity(Entity::new(
+            GeometryType::Circle(Circle { center: Vec3::new(10.0, 10.0, 0.0), radius: 2.0, normal: Vec3::unit_z() }),
+            "0".to_string(),
+        ));
+        let line_id = doc.add_entity(line(30.0, 30.0));
+
+        // An open line can't bound a hatch
+        assert!(doc.add_associative_hatch(Hatch::new("ANSI31", Vec::new()), &[line_id], "0".to_string()).is_none());
+        let id = doc
+            .add_associative_hatch(Hatch::new("ANSI31", Vec::new()), &[outer, island, line_id], "0".to_string())
+            .unwrap();
+        let hatch = |doc: &Document| match &doc.get_entity(id).unwrap().geometry {
+            GeometryType::Hatch(h) => h.clone(),
+            _ => unreachable!(),
+        };
+        assert_eq!(hatch(&doc).associated, vec![outer, island]);
+        assert_eq!(hatch(&doc).boundaries[1].len(), BOUNDARY_SEGMENTS);
+
+        // Stretching the outline regenerates the hatch and re-indexes it
+        if let Some(GeometryType::Polyline(p)) = doc.get_entity_mut(outer).map(|e| &mut e.geometry) {
+            p.vertices[1].position.x = 40.0;
+            p.vertices[2].position.x = 40.0;
+        }
+        doc.sync_spatial_index();
+        assert_eq!(hatch(&doc).boundaries[0][1].x, 40.0);
+        let region = BoundingBox::new(Vec3::new(35.0, 1.0, -1.0), Vec3::new(36.0, 2.0, 1.0));
+        assert!(doc.entities_in_box(&region).iter().any(|e| e.id == id));
+        assert!(hatch(&doc).lines().iter().any(|l| l.start.x.max(l.end.x) > 30.0));
+
+        // A deleted island drops out of the association
+        doc.remove_entity(island);
+        assert_eq!(doc.regenerate_hatches(), 1);
+        let regenerated = hatch(&doc);
+        assert_eq!(regenerated.associated, vec![outer]);
+        assert_eq!(regenerated.boundaries.len(), 1);
+    }
 }
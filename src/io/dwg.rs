@@ -166,7 +166,7 @@ impl DwgReader {
     /// Read DWG from a reader
     pub fn read<R: Read + Seek>(&self, reader: &mut R) -> DwgResult<Document> {
         // Read file header
-        let _header = self.read_header(reader)?;
+        let header = self.read_header(reader)?;
 
         // Verify version support
         self.verify_version(&header)?;
@@ -258,7 +258,7 @@ impl DwgReader {
 
         // Set document metadata from DWG header
         doc.metadata.title = format!("DWG Import ({})", header.version.code());
-        doc.metadata.created_date = chrono::Utc::now();
+        doc.metadata.created = chrono::Utc::now();
 
         // Convert DWG objects to document entities
         // This would be implemented with full object conversion
@@ -419,13 +419,13 @@ mod tests {
 
     #[test]
     fn test_dwg_reader_creation() {
-        let _reader = DwgReader::new();
+        let reader = DwgReader::new();
         assert!(!reader.strict_mode);
     }
 
     #[test]
     fn test_dwg_writer_creation() {
-        let _writer = DwgWriter::new(DwgVersion::R2018);
+        let writer = DwgWriter::new(DwgVersion::R2018);
         assert_eq!(writer.version, DwgVersion::R2018);
     }
 }
@@ -2,7 +2,12 @@
 // File I/O System - DXF Format Support
 // Agent 6 - File I/O System Developer
 
+use crate::geometry::{to_arc_polyline, BSpline, Ellipse2D, Point2D, SplineFit};
 use crate::io::document::*;
+use crate::io::health::PROXY_COUNT_VARIABLE;
+use crate::io::import::{to_curve, to_polyline, CurveFitOptions};
+use crate::io::redaction::RedactionProfile;
+use crate::io::tags::{self, XDATA_APP};
 use crate::io::units::Unit;
 use std::collections::HashMap;
 use std::fs::File;
@@ -33,6 +38,8 @@ pub type DxfResult<T> = Result<T, DxfError>;
 pub struct DxfReader {
     /// Progress callback (current, total)
     progress_callback: Option<Box<dyn Fn(usize, usize)>>,
+    /// Curve clean-up applied after reading
+    curve_fitting: CurveFitOptions,
 }
 
 impl DxfReader {
@@ -40,9 +47,16 @@ impl DxfReader {
     pub fn new() -> Self {
         Self {
             progress_callback: None,
+            curve_fitting: CurveFitOptions::default(),
         }
     }
 
+    /// Fit arcs to polylines and simplify or convert splines after reading
+    pub fn with_curve_fitting(mut self, options: CurveFitOptions) -> Self {
+        self.curve_fitting = options;
+        self
+    }
+
     /// Set a progress callback
     pub fn with_progress<F>(mut self, callback: F) -> Self
     where
@@ -55,7 +69,7 @@ impl DxfReader {
     /// Read a DXF file
     pub fn read_file<P: AsRef<Path>>(&self, path: P) -> DxfResult<Document> {
         let file = File::open(path)?;
-        let _reader = BufReader::new(file);
+        let reader = BufReader::new(file);
         self.read(reader)
     }
 
@@ -85,6 +99,8 @@ impl DxfReader {
             }
         }
 
+        self.curve_fitting.apply(&mut doc);
+
         Ok(doc)
     }
 
@@ -202,6 +218,7 @@ impl DxfReader {
             frozen: (flags & 2) != 0,
             locked: (flags & 4) != 0,
             plottable: true,
+            confidential: false,
         }))
     }
 
@@ -262,10 +279,13 @@ impl DxfReader {
         let mut entity_data: Vec<CodePair> = Vec::new();
         let total = section.entries.len();
         let mut processed = 0;
+        let mut proxies = 0;
 
         for entry in &section.entries {
             if entry.code == 0 && !entity_data.is_empty() {
-                if let Some(entity) = self.parse_entity(&entity_data)? {
+                if entity_data[0].value == "ACAD_PROXY_ENTITY" {
+                    proxies += 1;
+                } else if let Some(entity) = self.parse_entity(&entity_data)? {
                     doc.add_entity(entity);
                 }
                 entity_data.clear();
@@ -280,11 +300,19 @@ impl DxfReader {
 
         // Parse last entity
         if !entity_data.is_empty() {
-            if let Some(entity) = self.parse_entity(&entity_data)? {
+            if entity_data[0].value == "ACAD_PROXY_ENTITY" {
+                proxies += 1;
+            } else if let Some(entity) = self.parse_entity(&entity_data)? {
                 doc.add_entity(entity);
             }
         }
 
+        // Proxies can't be read, but the health report should say they were lost
+        if proxies > 0 {
+            doc.variables
+                .insert(PROXY_COUNT_VARIABLE.to_string(), proxies.to_string());
+        }
+
         Ok(())
     }
 
@@ -292,8 +320,10 @@ impl DxfReader {
         let mut entity_type = String::new();
         let mut layer = "0".to_string();
         let mut color: Option<Color> = None;
+        let mut xdata_app: Option<&str> = None;
+        let mut xdata = Vec::new();
 
-        // First pass: get entity type and basic properties
+        // First pass: get entity type, basic properties and our extended data
         for pair in data {
             match pair.code {
                 0 => entity_type = pair.value.clone(),
@@ -303,6 +333,8 @@ impl DxfReader {
                         color = Some(Color::from_autocad_index(color_idx));
                     }
                 }
+                1001 => xdata_app = Some(pair.value.as_str()),
+                1000 if xdata_app == Some(XDATA_APP) => xdata.push(pair.value.clone()),
                 _ => {}
             }
         }
@@ -326,6 +358,7 @@ impl DxfReader {
         if let Some(geom) = geometry {
             let mut entity = Entity::new(geom, layer);
             entity.color = color;
+            tags::apply_xdata(&mut entity, &xdata);
             Ok(Some(entity))
         } else {
             Ok(None)
@@ -499,30 +532,51 @@ impl DxfReader {
 
     fn parse_spline(&self, data: &[CodePair]) -> DxfResult<Option<GeometryType>> {
         let mut degree = 3;
-        let mut control_points = Vec::new();
         let mut knots = Vec::new();
-        let mut x = 0.0;
-        let mut y = 0.0;
-        let mut z = 0.0;
+        // Coordinates by group code: 10 control points, 11 fit points,
+        // 12/13 start and end tangents
+        let mut coords: HashMap<i32, Vec<f64>> = HashMap::new();
 
         for pair in data {
             match pair.code {
                 71 => degree = pair.value.parse().unwrap_or(3),
-                10 => {
-                    if !control_points.is_empty() {
-                        control_points.push(Vec3::new(x, y, z));
-                    }
-                    x = pair.value.parse().unwrap_or(0.0);
-                }
-                20 => y = pair.value.parse().unwrap_or(0.0),
-                30 => z = pair.value.parse().unwrap_or(0.0),
                 40 => knots.push(pair.value.parse().unwrap_or(0.0)),
+                10..=13 | 20..=23 | 30..=33 => coords
+                    .entry(pair.code)
+                    .or_default()
+                    .push(pair.value.parse().unwrap_or(0.0)),
                 _ => {}
             }
         }
 
-        // Add last control point
-        control_points.push(Vec3::new(x, y, z));
+        let points = |code: i32| -> Vec<Vec3> {
+            let axis = |c: i32, i: usize| coords.get(&c).and_then(|v| v.get(i)).copied().unwrap_or(0.0);
+            (0..coords.get(&code).map_or(0, Vec::len))
+                .map(|i| Vec3::new(axis(code, i), axis(code + 10, i), axis(code + 20, i)))
+                .collect()
+        };
+        let mut control_points = points(10);
+        let fit_points = points(11);
+
+        // Fit-point splines may carry no control points; rebuild them
+        if control_points.is_empty() && fit_points.len() >= 2 {
+            let fit = SplineFit {
+                degree,
+                start_tangent: points(12).first().map(|t| Point2D::new(t.x, t.y)),
+                end_tangent: points(13).first().map(|t| Point2D::new(t.x, t.y)),
+                ..Default::default()
+            };
+            let plan: Vec<Point2D> = fit_points.iter().map(|p| Point2D::new(p.x, p.y)).collect();
+            if let Some(curve) = BSpline::interpolate(&plan, &fit) {
+                let z = fit_points[0].z;
+                control_points = curve.control_points.iter().map(|p| Vec3::new(p.x, p.y, z)).collect();
+                knots = curve.knots;
+                degree = curve.degree;
+            }
+        }
+        if control_points.is_empty() {
+            return Ok(None);
+        }
 
         Ok(Some(GeometryType::Spline(Spline {
             degree,
@@ -639,6 +693,11 @@ impl Default for DxfReader {
 pub struct DxfWriter {
     /// DXF version to write
     version: DxfVersion,
+    /// Content withheld from the output
+    redaction: Option<RedactionProfile>,
+    /// Largest deviation when R12 output replaces splines and ellipses
+    /// with arc polylines
+    curve_tolerance: f64,
     /// Progress callback
     progress_callback: Option<Box<dyn Fn(usize, usize)>>,
 }
@@ -648,10 +707,26 @@ impl DxfWriter {
     pub fn new(version: DxfVersion) -> Self {
         Self {
             version,
+            redaction: None,
+            curve_tolerance: 0.01,
             progress_callback: None,
         }
     }
 
+    /// Withhold content by `profile`; rasterizing is not possible in DXF,
+    /// so withheld layers are always stripped
+    pub fn with_redaction(mut self, profile: RedactionProfile) -> Self {
+        self.redaction = Some(profile);
+        self
+    }
+
+    /// Set the largest deviation allowed when splines and ellipses are
+    /// written as arc polylines for R12, which has neither
+    pub fn with_curve_tolerance(mut self, tolerance: f64) -> Self {
+        self.curve_tolerance = tolerance;
+        self
+    }
+
     /// Set a progress callback
     pub fn with_progress<F>(mut self, callback: F) -> Self
     where
@@ -664,12 +739,21 @@ impl DxfWriter {
     /// Write a document to a DXF file
     pub fn write_file<P: AsRef<Path>>(&self, doc: &Document, path: P) -> DxfResult<()> {
         let file = File::create(path)?;
-        let _writer = BufWriter::new(file);
+        let writer = BufWriter::new(file);
         self.write(doc, writer)
     }
 
     /// Write DXF to a writer
     pub fn write<W: Write>(&self, doc: &Document, mut writer: W) -> DxfResult<()> {
+        let redacted;
+        let doc = match &self.redaction {
+            Some(profile) => {
+                redacted = profile.redact(doc).shared;
+                &redacted
+            }
+            None => doc,
+        };
+
         // Write header section
         self.write_header(&mut writer, doc)?;
 
@@ -707,6 +791,12 @@ impl DxfWriter {
         writeln!(writer, " 70")?;
         writeln!(writer, "{}", doc.settings.units.to_dxf_code())?;
 
+        // Imperial or metric hatch patterns and linetypes
+        writeln!(writer, "  9")?;
+        writeln!(writer, "$MEASUREMENT")?;
+        writeln!(writer, " 70")?;
+        writeln!(writer, "{}", if doc.settings.units.is_imperial() { 0 } else { 1 })?;
+
         writeln!(writer, "  0")?;
         writeln!(writer, "ENDSEC")?;
 
@@ -733,6 +823,23 @@ impl DxfWriter {
 
         writeln!(writer, "  0")?;
         writeln!(writer, "ENDTAB")?;
+
+        // Register the application name our extended entity data uses
+        writeln!(writer, "  0")?;
+        writeln!(writer, "TABLE")?;
+        writeln!(writer, "  2")?;
+        writeln!(writer, "APPID")?;
+        writeln!(writer, " 70")?;
+        writeln!(writer, "1")?;
+        writeln!(writer, "  0")?;
+        writeln!(writer, "APPID")?;
+        writeln!(writer, "  2")?;
+        writeln!(writer, "{}", XDATA_APP)?;
+        writeln!(writer, " 70")?;
+        writeln!(writer, "0")?;
+        writeln!(writer, "  0")?;
+        writeln!(writer, "ENDTAB")?;
+
         writeln!(writer, "  0")?;
         writeln!(writer, "ENDSEC")?;
 
@@ -824,6 +931,29 @@ impl DxfWriter {
     }
 
     fn write_entity<W: Write>(&self, writer: &mut W, entity: &Entity) -> DxfResult<()> {
+        if matches!(self.version, DxfVersion::R12) {
+            // R12 has no ELLIPSE, SPLINE or LWPOLYLINE
+            match &entity.geometry {
+                GeometryType::Ellipse(e) => {
+                    let center = Point2D::new(e.center.x, e.center.y);
+                    let ellipse = Ellipse2D::new(center, e.major_axis, e.minor_axis, e.rotation);
+                    let arcs = to_arc_polyline(&ellipse, self.curve_tolerance);
+                    return self.write_r12_polyline(writer, entity, &to_polyline(&arcs, e.center.z));
+                }
+                GeometryType::Spline(s) => {
+                    let elevation = s.control_points.first().map_or(0.0, |p| p.z);
+                    let arcs = match to_curve(s) {
+                        Some(Ok(spline)) => to_arc_polyline(&spline, self.curve_tolerance),
+                        Some(Err(nurbs)) => to_arc_polyline(&nurbs, self.curve_tolerance),
+                        None => return Ok(()),
+                    };
+                    return self.write_r12_polyline(writer, entity, &to_polyline(&arcs, elevation));
+                }
+                GeometryType::Polyline(p) => return self.write_r12_polyline(writer, entity, p),
+                _ => {}
+            }
+        }
+
         match &entity.geometry {
             GeometryType::Point(p) => self.write_point(writer, entity, p)?,
             GeometryType::Line(l) => self.write_line(writer, entity, l)?,
@@ -835,9 +965,25 @@ impl DxfWriter {
             GeometryType::Text(t) => self.write_text(writer, entity, t)?,
             GeometryType::MText(t) => self.write_mtext(writer, entity, t)?,
             GeometryType::Insert(i) => self.write_insert(writer, entity, i)?,
-            _ => {} // Skip unsupported types
+            _ => return Ok(()), // Skip unsupported types
         }
 
+        self.write_xdata(writer, entity)
+    }
+
+    /// Tags and classification as extended entity data, which must follow
+    /// all other group codes of the entity
+    fn write_xdata<W: Write>(&self, writer: &mut W, entity: &Entity) -> DxfResult<()> {
+        let values = tags::to_xdata(entity);
+        if values.is_empty() {
+            return Ok(());
+        }
+        writeln!(writer, "1001")?;
+        writeln!(writer, "{}", XDATA_APP)?;
+        for value in values {
+            writeln!(writer, "1000")?;
+            writeln!(writer, "{}", value)?;
+        }
         Ok(())
     }
 
@@ -928,6 +1074,7 @@ impl DxfWriter {
         writeln!(writer, "{}", polyline.vertices.len())?;
         writeln!(writer, " 70")?;
         writeln!(writer, "{}", if polyline.closed { 1 } else { 0 })?;
+        self.write_xdata(writer, entity)?;
 
         for vertex in &polyline.vertices {
             writeln!(writer, " 10")?;
@@ -943,6 +1090,38 @@ impl DxfWriter {
         Ok(())
     }
 
+    /// POLYLINE followed by VERTEX entities and a SEQEND
+    fn write_r12_polyline<W: Write>(&self, writer: &mut W, entity: &Entity, polyline: &Polyline) -> DxfResult<()> {
+        let elevation = polyline.vertices.first().map_or(0.0, |v| v.position.z);
+        self.write_common(writer, entity, "POLYLINE")?;
+        writeln!(writer, " 66")?;
+        writeln!(writer, "1")?;
+        writeln!(writer, " 10")?;
+        writeln!(writer, "0")?;
+        writeln!(writer, " 20")?;
+        writeln!(writer, "0")?;
+        writeln!(writer, " 30")?;
+        writeln!(writer, "{}", elevation)?;
+        writeln!(writer, " 70")?;
+        writeln!(writer, "{}", if polyline.closed { 1 } else { 0 })?;
+
+        for vertex in &polyline.vertices {
+            self.write_common(writer, entity, "VERTEX")?;
+            writeln!(writer, " 10")?;
+            writeln!(writer, "{}", vertex.position.x)?;
+            writeln!(writer, " 20")?;
+            writeln!(writer, "{}", vertex.position.y)?;
+            writeln!(writer, " 30")?;
+            writeln!(writer, "{}", vertex.position.z)?;
+            if vertex.bulge.abs() > 1e-10 {
+                writeln!(writer, " 42")?;
+                writeln!(writer, "{}", vertex.bulge)?;
+            }
+        }
+
+        self.write_common(writer, entity, "SEQEND")
+    }
+
     fn write_spline<W: Write>(&self, writer: &mut W, entity: &Entity, spline: &Spline) -> DxfResult<()> {
         self.write_common(writer, entity, "SPLINE")?;
         writeln!(writer, " 71")?;
@@ -1153,7 +1332,7 @@ mod tests {
         ));
 
         let mut buffer = Vec::new();
-        let _writer = DxfWriter::new(DxfVersion::R2018);
+        let writer = DxfWriter::new(DxfVersion::R2018);
         writer.write(&doc, &mut buffer).unwrap();
 
         // Basic check that something was written
@@ -1161,4 +1340,130 @@ mod tests {
         let content = String::from_utf8(buffer).unwrap();
         assert!(content.contains("LINE"));
     }
+    #[test]
+    fn test_read_counts_proxy_entities() {
+        let dxf = "0\nSECTION\n2\nENTITIES\n\
+                   0\nLINE\n8\n0\n10\n0\n20\n0\n30\n0\n11\n1\n21\n1\n31\n0\n\
+                   0\nACAD_PROXY_ENTITY\n8\n0\n90\n498\n\
+                   0\nACAD_PROXY_ENTITY\n8\n0\n90\n498\n\
+                   0\nENDSEC\n0\nEOF\n";
+
+        let doc = DxfReader::new().read(dxf.as_bytes()).unwrap();
+        assert_eq!(doc.entities.len(), 1);
+        assert_eq!(doc.variables[PROXY_COUNT_VARIABLE], "2");
+    }
+
+    #[test]
+    fn test_read_fit_point_spline() {
+        let dxf = "0\nSECTION\n2\nENTITIES\n\
+                   0\nSPLINE\n8\n0\n71\n3\n74\n3\n\
+                   12\n0\n22\n1\n32\n0\n13\n1\n23\n0\n33\n0\n\
+                   11\n0\n21\n0\n31\n2\n11\n5\n21\n3\n31\n2\n11\n10\n21\n0\n31\n2\n\
+                   0\nENDSEC\n0\nEOF\n";
+
+        let doc = DxfReader::new().read(dxf.as_bytes()).unwrap();
+        let spline = doc.entities.iter().find_map(|e| match &e.geometry {
+            GeometryType::Spline(s) => Some(s.clone()),
+            _ => None,
+        });
+        let spline = spline.unwrap();
+        // Three fit points and two end tangents
+        assert_eq!(spline.control_points.len(), 5);
+        assert_eq!(spline.knots.len(), 9);
+        let (first, last) = (spline.control_points[0], spline.control_points[4]);
+        assert!(first.x.abs() < 1e-9 && first.y.abs() < 1e-9 && first.z == 2.0);
+        assert!((last.x - 10.0).abs() < 1e-9 && last.y.abs() < 1e-9 && last.z == 2.0);
+        // Leaves the first point straight up
+        assert!(spline.control_points[1].x.abs() < 1e-9 && spline.control_points[1].y > 0.0);
+    }
+
+    #[test]
+    fn test_r12_writes_curves_as_polylines() {
+        let mut doc = Document::new();
+        doc.add_entity(Entity::new(
+            GeometryType::Spline(Spline {
+                degree: 2,
+                control_points: vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(5.0, 8.0, 0.0), Vec3::new(10.0, 0.0, 0.0)],
+                knots: vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+                weights: None,
+                closed: false,
+            }),
+            "0".to_string(),
+        ));
+        doc.add_entity(Entity::new(
+            GeometryType::Ellipse(Ellipse {
+                center: Vec3::new(20.0, 0.0, 0.0),
+                major_axis: 4.0,
+                minor_axis: 2.0,
+                rotation: 0.0,
+                normal: Vec3::unit_z(),
+            }),
+            "0".to_string(),
+        ));
+
+        let mut buffer = Vec::new();
+        DxfWriter::new(DxfVersion::R12)
+            .with_curve_tolerance(0.001)
+            .write(&doc, &mut buffer)
+            .unwrap();
+        let content = String::from_utf8(buffer).unwrap();
+        assert!(!content.contains("\nSPLINE\n") && !content.contains("\nELLIPSE\n"));
+        assert!(!content.contains("LWPOLYLINE"));
+        assert_eq!(content.matches("\nPOLYLINE\n").count(), 2);
+        assert_eq!(content.matches("\nSEQEND\n").count(), 2);
+        assert!(content.matches("\nVERTEX\n").count() > 4);
+    }
+
+    #[test]
+    fn test_redacted_write() {
+        let mut doc = Document::new();
+        let mut layer = doc.get_layer("0").unwrap().clone();
+        layer.name = "X-PRICING".to_string();
+        layer.confidential = true;
+        doc.add_layer(layer);
+        for layer in ["0", "X-PRICING"] {
+            doc.add_entity(Entity::new(
+                GeometryType::Line(Line {
+                    start: Vec3::new(0.0, 0.0, 0.0),
+                    end: Vec3::new(10.0, 10.0, 0.0),
+                }),
+                layer.to_string(),
+            ));
+        }
+
+        let mut buffer = Vec::new();
+        DxfWriter::new(DxfVersion::R2018)
+            .with_redaction(RedactionProfile::default())
+            .write(&doc, &mut buffer)
+            .unwrap();
+        let content = String::from_utf8(buffer).unwrap();
+        assert_eq!(content.matches("\nLINE\n").count(), 1);
+        assert!(!content.contains("X-PRICING"));
+    }
+
+    #[test]
+    fn test_tags_roundtrip_as_xdata() {
+        let mut doc = Document::new();
+        let mut wall = Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::new(0.0, 0.0, 0.0),
+                end: Vec3::new(10.0, 0.0, 0.0),
+            }),
+            "0".to_string(),
+        );
+        tags::add_tag(&mut wall, "fire-rated").unwrap();
+        tags::add_tag(&mut wall, "phase-2").unwrap();
+        tags::set_classification(&mut wall, Some("Structural/Masonry")).unwrap();
+        doc.add_entity(wall);
+
+        let mut buffer = Vec::new();
+        DxfWriter::new(DxfVersion::R2018).write(&doc, &mut buffer).unwrap();
+        let content = String::from_utf8(buffer).unwrap();
+        assert!(content.contains("APPID\n  2\nCADDY"));
+        assert!(content.contains("1001\nCADDY\n1000\nTAG:fire-rated"));
+
+        let read = DxfReader::new().read(content.as_bytes()).unwrap();
+        assert_eq!(tags::tags(&read.entities[0]), vec!["fire-rated", "phase-2"]);
+        assert_eq!(tags::classification(&read.entities[0]), Some("Structural/Masonry"));
+    }
 }
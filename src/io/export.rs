@@ -3,7 +3,11 @@
 // Agent 6 - File I/O System Developer
 
 use crate::io::document::*;
+#[cfg(feature = "native")]
+use crate::io::raster::{RasterFormat, RasterOutput};
 
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::PI;
 use std::fs::File;
 use std::io::{self, Write};
 use std::path::Path;
@@ -41,8 +45,19 @@ pub struct SvgExportSettings {
     pub include_layer_comments: bool,
     /// View box (auto-calculate if None)
     pub view_box: Option<(f64, f64, f64, f64)>,
-    /// Precision (decimal places)
+    /// Coordinate precision (decimal places, trailing zeros are trimmed)
     pub precision: usize,
+    /// Emit a `<style>` block with one class per layer and linetype; only
+    /// properties that differ from the layer are written on the elements
+    pub css_classes: bool,
+    /// Font family list used for text styles missing from `font_map`
+    pub font_family: String,
+    /// Text style name to CSS font family
+    pub font_map: HashMap<String, String>,
+    /// Multiplier applied to linetype dash lengths
+    pub linetype_scale: f64,
+    /// Fill hatches with SVG patterns (boundaries only when false)
+    pub hatch_fills: bool,
 }
 
 impl Default for SvgExportSettings {
@@ -56,10 +71,27 @@ impl Default for SvgExportSettings {
             include_layer_comments: true,
             view_box: None,
             precision: 3,
+            css_classes: true,
+            font_family: "Arial, Helvetica, sans-serif".to_string(),
+            font_map: HashMap::new(),
+            linetype_scale: 1.0,
+            hatch_fills: true,
         }
     }
 }
 
+/// Line spacing of ANSI31 at scale 1 (1/8 inch), used for every line pattern
+const HATCH_SPACING: f64 = 3.175;
+
+/// Pattern fill shared by all hatches with the same pattern, scale, angle and color
+#[derive(Debug, Clone, PartialEq)]
+struct HatchFill {
+    pattern: String,
+    scale: f64,
+    angle: f64,
+    color: Color,
+}
+
 /// SVG exporter
 pub struct SvgExporter {
     settings: SvgExportSettings,
@@ -79,14 +111,14 @@ impl SvgExporter {
 
     /// Export to a writer
     pub fn export_to_writer<W: Write>(&self, doc: &Document, writer: &mut W) -> ExportResult<()> {
-        // Calculate view box if not specified
+        // Calculate view box if not specified (Y is flipped, so the top edge is -max.y)
         let view_box = if let Some(vb) = self.settings.view_box {
             vb
         } else if let Some(bbox) = doc.bounding_box() {
             let margin = 10.0;
             (
                 bbox.min.x - margin,
-                bbox.min.y - margin,
+                -bbox.max.y - margin,
                 (bbox.max.x - bbox.min.x) + 2.0 * margin,
                 (bbox.max.y - bbox.min.y) + 2.0 * margin,
             )
@@ -109,6 +141,20 @@ impl SvgExporter {
             self.fmt(view_box.3)
         )?;
 
+        let layers = self.layer_order(doc);
+        let hatches = self.collect_hatches(doc, &layers);
+
+        if self.settings.css_classes || !hatches.is_empty() {
+            writeln!(writer, "  <defs>")?;
+            if self.settings.css_classes {
+                self.write_styles(writer, doc, &layers)?;
+            }
+            for (index, fill) in hatches.iter().enumerate() {
+                self.write_hatch_pattern(writer, index, fill)?;
+            }
+            writeln!(writer, "  </defs>")?;
+        }
+
         // Add background if specified
         if let Some(bg) = self.settings.background {
             writeln!(
@@ -119,28 +165,34 @@ impl SvgExporter {
         }
 
         // Group entities by layer
-        let mut layers_written = std::collections::HashSet::new();
-
-        for layer_name in doc.layer_names() {
-            let _entities = doc.entities_on_layer(&layer_name);
+        for layer_name in &layers {
+            let entities: Vec<&Entity> = doc
+                .entities_on_layer(layer_name)
+                .into_iter()
+                .filter(|e| e.visible)
+                .collect();
             if entities.is_empty() {
                 continue;
             }
 
             if self.settings.include_layer_comments {
-                writeln!(writer, "  <!-- Layer: {} -->", layer_name)?;
+                writeln!(writer, "  <!-- Layer: {} -->", self.escape_xml(layer_name))?;
             }
 
-            writeln!(writer, "  <g id=\"layer-{}\">", self.escape_xml(&layer_name))?;
+            let ident = css_ident(layer_name);
+            writeln!(
+                writer,
+                "  <g id=\"layer-{}\" class=\"layer layer-{}\" data-layer=\"{}\">",
+                ident,
+                ident,
+                self.escape_xml(layer_name)
+            )?;
 
             for entity in entities {
-                if entity.visible {
-                    self.write_entity(writer, entity, doc)?;
-                }
+                self.write_entity(writer, entity, doc, &hatches)?;
             }
 
             writeln!(writer, "  </g>")?;
-            layers_written.insert(layer_name);
         }
 
         // Write closing tag
@@ -149,73 +201,245 @@ impl SvgExporter {
         Ok(())
     }
 
+    /// Layers in the order their first entity appears, skipping hidden and frozen layers
+    fn layer_order(&self, doc: &Document) -> Vec<String> {
+        let mut seen = HashSet::new();
+        doc.entities
+            .iter()
+            .filter(|e| seen.insert(e.layer.as_str()))
+            .filter(|e| {
+                doc.get_layer(&e.layer)
+                    .is_none_or(|l| l.visible && !l.frozen)
+            })
+            .map(|e| e.layer.clone())
+            .collect()
+    }
+
+    fn collect_hatches(&self, doc: &Document, layers: &[String]) -> Vec<HatchFill> {
+        let mut fills = Vec::new();
+        if !self.settings.hatch_fills {
+            return fills;
+        }
+
+        for entity in doc
+            .entities
+            .iter()
+            .filter(|e| e.visible && layers.contains(&e.layer))
+        {
+            if let GeometryType::Hatch(h) = &entity.geometry {
+                let fill = self.hatch_fill(h, self.get_entity_color(entity, doc));
+                if fill.pattern != "SOLID" && !fills.contains(&fill) {
+                    fills.push(fill);
+                }
+            }
+        }
+
+        fills
+    }
+
+    fn write_styles<W: Write>(
+        &self,
+        writer: &mut W,
+        doc: &Document,
+        layers: &[String],
+    ) -> ExportResult<()> {
+        writeln!(writer, "    <style>")?;
+        writeln!(
+            writer,
+            "      .layer {{ fill: none; stroke-width: {}; stroke-linecap: round; stroke-linejoin: round; }}",
+            self.fmt(self.settings.stroke_width)
+        )?;
+        writeln!(writer, "      .layer text, .layer .point {{ stroke: none; }}")?;
+        writeln!(
+            writer,
+            "      .layer text {{ font-family: {}; }}",
+            self.escape_xml(&self.settings.font_family)
+        )?;
+
+        for name in layers {
+            let ident = css_ident(name);
+            let color = self.color_to_svg(&self.layer_color(name, doc));
+            let dashes = doc
+                .get_layer(name)
+                .and_then(|l| self.dash_array(&l.line_type))
+                .map(|d| format!(" stroke-dasharray: {};", d))
+                .unwrap_or_default();
+            writeln!(writer, "      .layer-{} {{ stroke: {};{} }}", ident, color, dashes)?;
+            writeln!(
+                writer,
+                "      .layer-{} text, .layer-{} .point {{ fill: {}; }}",
+                ident, ident, color
+            )?;
+        }
+
+        // Entity-level linetype overrides
+        let mut line_types: Vec<&LineType> = Vec::new();
+        for entity in doc.entities.iter().filter(|e| layers.contains(&e.layer)) {
+            if let Some(lt) = &entity.line_type {
+                if !line_types.iter().any(|seen| seen.name() == lt.name()) {
+                    line_types.push(lt);
+                }
+            }
+        }
+        for lt in line_types {
+            writeln!(
+                writer,
+                "      .layer .lt-{} {{ stroke-dasharray: {}; }}",
+                line_type_class(lt),
+                self.dash_array(lt).unwrap_or_else(|| "none".to_string())
+            )?;
+        }
+
+        writeln!(writer, "    </style>")?;
+        Ok(())
+    }
+
+    fn write_hatch_pattern<W: Write>(
+        &self,
+        writer: &mut W,
+        index: usize,
+        fill: &HatchFill,
+    ) -> ExportResult<()> {
+        // (base angle in degrees, crossed, dotted)
+        let (base, cross, dots) = match fill.pattern.as_str() {
+            "LINE" => (0.0, false, false),
+            "NET" => (0.0, true, false),
+            "ANSI37" => (45.0, true, false),
+            "DOTS" => (0.0, false, true),
+            // ANSI31 and patterns without a built-in definition
+            _ => (45.0, false, false),
+        };
+
+        let size = HATCH_SPACING * fill.scale;
+        let half = size / 2.0;
+        let width = self.settings.stroke_width.min(size / 4.0);
+        let color = self.color_to_svg(&fill.color);
+
+        writeln!(
+            writer,
+            "    <pattern id=\"hatch-{}\" patternUnits=\"userSpaceOnUse\" width=\"{}\" height=\"{}\" \
+             patternTransform=\"rotate({})\">",
+            index,
+            self.fmt(size),
+            self.fmt(size),
+            self.fmt(-(base + fill.angle.to_degrees()))
+        )?;
+        if dots {
+            writeln!(
+                writer,
+                "      <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" />",
+                self.fmt(half),
+                self.fmt(half),
+                self.fmt(width),
+                color
+            )?;
+        } else {
+            let mut d = format!("M 0 {} H {}", self.fmt(half), self.fmt(size));
+            if cross {
+                d.push_str(&format!(" M {} 0 V {}", self.fmt(half), self.fmt(size)));
+            }
+            writeln!(
+                writer,
+                "      <path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" />",
+                d,
+                color,
+                self.fmt(width)
+            )?;
+        }
+        writeln!(writer, "    </pattern>")?;
+
+        Ok(())
+    }
+
     fn write_entity<W: Write>(
         &self,
         writer: &mut W,
         entity: &Entity,
         doc: &Document,
+        hatches: &[HatchFill],
     ) -> ExportResult<()> {
-        let stroke = self.get_entity_color(entity, doc);
-        let stroke_width = self.settings.stroke_width;
+        let color = self.get_entity_color(entity, doc);
+        let css = self.settings.css_classes;
+        // With a stylesheet only overrides are written; without one, everything is
+        let color_override = if css { entity.color } else { Some(color) };
+
+        let mut class = entity.geometry.type_name().to_lowercase();
+        let mut stroke: Vec<(&str, String)> = Vec::new();
+        let mut fill: Vec<(&str, String)> = Vec::new();
+        if let Some(c) = color_override {
+            stroke.push(("stroke", self.color_to_svg(&c)));
+            fill.push(("fill", self.color_to_svg(&c)));
+        }
+        if css {
+            if let Some(lt) = &entity.line_type {
+                class.push_str(" lt-");
+                class.push_str(&line_type_class(lt));
+            }
+        } else {
+            stroke.push(("fill", "none".to_string()));
+            stroke.push(("stroke-width", self.fmt(self.settings.stroke_width)));
+            if let Some(dashes) = self.line_type(entity, doc).and_then(|lt| self.dash_array(lt)) {
+                stroke.push(("stroke-dasharray", dashes));
+                stroke.push(("stroke-linecap", "round".to_string()));
+            }
+        }
+        let stroke = self.paint(&stroke);
 
         match &entity.geometry {
             GeometryType::Point(p) => {
                 writeln!(
                     writer,
-                    "    <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" />",
+                    "    <circle class=\"{}\" cx=\"{}\" cy=\"{}\" r=\"{}\"{} />",
+                    class,
                     self.fmt(p.position.x),
                     self.fmt(-p.position.y), // SVG Y is inverted
-                    stroke_width,
-                    self.color_to_svg(&stroke)
+                    self.fmt(self.settings.stroke_width),
+                    self.paint(&fill)
                 )?;
             }
             GeometryType::Line(l) => {
                 writeln!(
                     writer,
-                    "    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\" />",
+                    "    <line class=\"{}\" x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"{} />",
+                    class,
                     self.fmt(l.start.x),
                     self.fmt(-l.start.y),
                     self.fmt(l.end.x),
                     self.fmt(-l.end.y),
-                    self.color_to_svg(&stroke),
-                    stroke_width
+                    stroke
                 )?;
             }
             GeometryType::Circle(c) => {
                 writeln!(
                     writer,
-                    "    <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" />",
+                    "    <circle class=\"{}\" cx=\"{}\" cy=\"{}\" r=\"{}\"{} />",
+                    class,
                     self.fmt(c.center.x),
                     self.fmt(-c.center.y),
                     self.fmt(c.radius),
-                    self.color_to_svg(&stroke),
-                    stroke_width
+                    stroke
                 )?;
             }
             GeometryType::Arc(a) => {
-                let path = self.arc_to_path(a);
                 writeln!(
                     writer,
-                    "    <path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" />",
-                    path,
-                    self.color_to_svg(&stroke),
-                    stroke_width
+                    "    <path class=\"{}\" d=\"{}\"{} />",
+                    class,
+                    self.arc_to_path(a),
+                    stroke
                 )?;
             }
             GeometryType::Ellipse(e) => {
                 writeln!(
                     writer,
-                    "    <ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\" transform=\"rotate({} {} {})\" \
-                     fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" />",
+                    "    <ellipse class=\"{}\" cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\"{}{} />",
+                    class,
                     self.fmt(e.center.x),
                     self.fmt(-e.center.y),
                     self.fmt(e.major_axis),
                     self.fmt(e.minor_axis),
-                    self.fmt(-e.rotation.to_degrees()),
-                    self.fmt(e.center.x),
-                    self.fmt(-e.center.y),
-                    self.color_to_svg(&stroke),
-                    stroke_width
+                    self.rotate(e.rotation, e.center),
+                    stroke
                 )?;
             }
             GeometryType::Polyline(p) => {
@@ -228,49 +452,93 @@ impl SvgExporter {
                 let poly_type = if p.closed { "polygon" } else { "polyline" };
                 writeln!(
                     writer,
-                    "    <{} points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" />",
+                    "    <{} class=\"{}\" points=\"{}\"{} />",
                     poly_type,
+                    class,
                     points.join(" "),
-                    self.color_to_svg(&stroke),
-                    stroke_width
+                    stroke
                 )?;
             }
             GeometryType::Spline(s) => {
                 // Simplified spline rendering as polyline
-                let path = self.spline_to_path(s);
                 writeln!(
                     writer,
-                    "    <path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" />",
+                    "    <path class=\"{}\" d=\"{}\"{} />",
+                    class,
+                    self.spline_to_path(s),
+                    stroke
+                )?;
+            }
+            GeometryType::Hatch(h) => {
+                let path = self.hatch_to_path(h);
+                if path.is_empty() {
+                    return Ok(());
+                }
+                if !self.settings.hatch_fills {
+                    writeln!(
+                        writer,
+                        "    <path class=\"{}\" d=\"{}\"{} />",
+                        class, path, stroke
+                    )?;
+                    return Ok(());
+                }
+
+                let hatch_fill = self.hatch_fill(h, color);
+                let paint = match hatches.iter().position(|f| *f == hatch_fill) {
+                    Some(index) => format!("url(#hatch-{})", index),
+                    None => self.color_to_svg(&color),
+                };
+                writeln!(
+                    writer,
+                    "    <path class=\"{}\" d=\"{}\" fill-rule=\"evenodd\"{} />",
+                    class,
                     path,
-                    self.color_to_svg(&stroke),
-                    stroke_width
+                    self.paint(&[("fill", paint), ("stroke", "none".to_string())])
                 )?;
             }
             GeometryType::Text(t) => {
+                if let Some(family) = self.font_family(&t.style) {
+                    fill.push(("font-family", family));
+                }
                 writeln!(
                     writer,
-                    "    <text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"{}\" transform=\"rotate({} {} {})\">{}</text>",
+                    "    <text class=\"{}\" x=\"{}\" y=\"{}\" font-size=\"{}\"{}{}{}>{}</text>",
+                    class,
                     self.fmt(t.position.x),
                     self.fmt(-t.position.y),
                     self.fmt(t.height),
-                    self.color_to_svg(&stroke),
-                    self.fmt(-t.rotation.to_degrees()),
-                    self.fmt(t.position.x),
-                    self.fmt(-t.position.y),
+                    text_alignment(t.horizontal_alignment, t.vertical_alignment),
+                    self.rotate(t.rotation, t.position),
+                    self.paint(&fill),
                     self.escape_xml(&t.text)
                 )?;
             }
             GeometryType::MText(t) => {
-                // Basic MText rendering
+                if let Some(family) = self.font_family(&t.style) {
+                    fill.push(("font-family", family));
+                }
+                // The insertion point is the top-left corner of the first line
                 writeln!(
                     writer,
-                    "    <text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"{}\">{}</text>",
+                    "    <text class=\"{}\" x=\"{}\" y=\"{}\" font-size=\"{}\" dominant-baseline=\"hanging\"{}{}>",
+                    class,
                     self.fmt(t.position.x),
                     self.fmt(-t.position.y),
                     self.fmt(t.height),
-                    self.color_to_svg(&stroke),
-                    self.escape_xml(&t.text)
+                    self.rotate(t.rotation, t.position),
+                    self.paint(&fill)
                 )?;
+                let step = t.height * t.line_spacing.max(1.0) * 1.5;
+                for (i, line) in t.text.split("\\P").flat_map(|l| l.lines()).enumerate() {
+                    writeln!(
+                        writer,
+                        "      <tspan x=\"{}\" dy=\"{}\">{}</tspan>",
+                        self.fmt(t.position.x),
+                        self.fmt(if i == 0 { 0.0 } else { step }),
+                        self.escape_xml(line)
+                    )?;
+                }
+                writeln!(writer, "    </text>")?;
             }
             _ => {
                 // Unsupported entity types are silently skipped
@@ -293,8 +561,9 @@ impl SvgExporter {
 
         let large_arc = if angle_diff > PI { 1 } else { 0 };
 
+        // CAD arcs run counter-clockwise, the negative direction in SVG's Y-down space (sweep 0)
         format!(
-            "M {} {} A {} {} 0 {} 1 {} {}",
+            "M {} {} A {} {} 0 {} 0 {} {}",
             self.fmt(start_x),
             self.fmt(-start_y),
             self.fmt(arc.radius),
@@ -324,12 +593,110 @@ impl SvgExporter {
         path
     }
 
+    /// One closed subpath per boundary loop; islands cut out via the even-odd rule
+    fn hatch_to_path(&self, hatch: &Hatch) -> String {
+        let mut subpaths = Vec::new();
+        for boundary in hatch.boundaries.iter().filter(|b| b.len() > 2) {
+            let points: Vec<String> = boundary
+                .iter()
+                .map(|p| format!("{} {}", self.fmt(p.x), self.fmt(-p.y)))
+                .collect();
+            subpaths.push(format!("M {} Z", points.join(" L ")));
+        }
+        subpaths.join(" ")
+    }
+
+    fn hatch_fill(&self, hatch: &Hatch, color: Color) -> HatchFill {
+        HatchFill {
+            pattern: hatch.pattern.to_uppercase(),
+            scale: if hatch.scale > 0.0 { hatch.scale } else { 1.0 },
+            angle: hatch.angle,
+            color,
+        }
+    }
+
     fn get_entity_color(&self, entity: &Entity, doc: &Document) -> Color {
-        entity.color.unwrap_or_else(|| {
-            doc.get_layer(&entity.layer)
-                .map(|l| l.color)
-                .unwrap_or(Color::white())
-        })
+        entity
+            .color
+            .unwrap_or_else(|| self.layer_color(&entity.layer, doc))
+    }
+
+    fn layer_color(&self, layer: &str, doc: &Document) -> Color {
+        doc.get_layer(layer)
+            .map(|l| l.color)
+            .unwrap_or(Color::white())
+    }
+
+    fn line_type<'a>(&self, entity: &'a Entity, doc: &'a Document) -> Option<&'a LineType> {
+        entity
+            .line_type
+            .as_ref()
+            .or_else(|| doc.get_layer(&entity.layer).map(|l| &l.line_type))
+    }
+
+    /// `stroke-dasharray` value for a linetype, or None for continuous lines
+    fn dash_array(&self, line_type: &LineType) -> Option<String> {
+        let pattern = line_type.pattern();
+        if pattern.is_empty() {
+            return None;
+        }
+
+        let dashes: Vec<String> = pattern
+            .iter()
+            .map(|v| self.fmt(v.abs() * self.settings.linetype_scale))
+            .collect();
+        Some(dashes.join(","))
+    }
+
+    /// CSS font family for a text style, falling back to `font_family`.
+    /// Returns None when the stylesheet's default already applies.
+    fn font_family(&self, style: &str) -> Option<String> {
+        let fallback = &self.settings.font_family;
+        let family = match self.settings.font_map.get(style) {
+            Some(font) => Some(format!("{}, {}", font, fallback)),
+            None if !style.is_empty() && !style.eq_ignore_ascii_case("standard") => {
+                Some(format!("'{}', {}", style, fallback))
+            }
+            None => None,
+        };
+
+        match family {
+            None if !self.settings.css_classes => Some(fallback.clone()),
+            family => family,
+        }
+    }
+
+    /// Presentation properties as inline `style` when a stylesheet is present
+    /// (so they win over class rules), as attributes otherwise
+    fn paint(&self, props: &[(&str, String)]) -> String {
+        if props.is_empty() {
+            return String::new();
+        }
+
+        if self.settings.css_classes {
+            let style: Vec<String> = props
+                .iter()
+                .map(|(name, value)| format!("{}:{}", name, self.escape_xml(value)))
+                .collect();
+            format!(" style=\"{}\"", style.join(";"))
+        } else {
+            props
+                .iter()
+                .map(|(name, value)| format!(" {}=\"{}\"", name, self.escape_xml(value)))
+                .collect()
+        }
+    }
+
+    fn rotate(&self, angle: f64, center: Vec3) -> String {
+        if angle == 0.0 {
+            return String::new();
+        }
+        format!(
+            " transform=\"rotate({} {} {})\"",
+            self.fmt(-angle.to_degrees()),
+            self.fmt(center.x),
+            self.fmt(-center.y)
+        )
     }
 
     fn color_to_svg(&self, color: &Color) -> String {
@@ -337,7 +704,14 @@ impl SvgExporter {
     }
 
     fn fmt(&self, value: f64) -> String {
-        format!("{:.prec$}", value, prec = self.settings.precision)
+        let mut s = format!("{:.prec$}", value, prec = self.settings.precision);
+        if s.contains('.') {
+            s.truncate(s.trim_end_matches('0').trim_end_matches('.').len());
+        }
+        if s == "-0" {
+            s.remove(0);
+        }
+        s
     }
 
     fn escape_xml(&self, text: &str) -> String {
@@ -355,6 +729,37 @@ impl Default for SvgExporter {
     }
 }
 
+/// Layer or linetype name as a CSS class/ID fragment; other characters are hex-escaped
+fn css_ident(name: &str) -> String {
+    let mut ident = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+            ident.push(c);
+        } else {
+            ident.push_str(&format!("_{:x}_", c as u32));
+        }
+    }
+    ident
+}
+
+fn line_type_class(line_type: &LineType) -> String {
+    css_ident(&line_type.name().to_lowercase())
+}
+
+fn text_alignment(horizontal: TextAlignment, vertical: TextAlignment) -> String {
+    let anchor = match horizontal {
+        TextAlignment::Center | TextAlignment::Middle => " text-anchor=\"middle\"",
+        TextAlignment::Right => " text-anchor=\"end\"",
+        _ => "",
+    };
+    let baseline = match vertical {
+        TextAlignment::Top => " dominant-baseline=\"hanging\"",
+        TextAlignment::Middle | TextAlignment::Center => " dominant-baseline=\"central\"",
+        _ => "",
+    };
+    format!("{}{}", anchor, baseline)
+}
+
 /// PDF export settings
 #[derive(Debug, Clone)]
 pub struct PdfExportSettings {
@@ -382,6 +787,16 @@ impl Default for PdfExportSettings {
     }
 }
 
+impl PdfExportSettings {
+    /// Default settings on the document's paper size
+    pub fn for_document(doc: &Document) -> Self {
+        Self {
+            page_size: doc.settings.paper_size,
+            ..Self::default()
+        }
+    }
+}
+
 /// PDF exporter (basic implementation)
 /// Note: A full implementation would use a library like printpdf
 pub struct PdfExporter {
@@ -419,6 +834,14 @@ pub struct RasterExportSettings {
     pub anti_aliasing: u8,
     /// DPI (for print quality)
     pub dpi: u32,
+    /// Tile edge in pixels; large images are rendered tile by tile
+    pub tile_size: u32,
+    /// Area to export in drawing units (None = drawing extents)
+    pub window: Option<((f64, f64), (f64, f64))>,
+    /// Pen width in millimeters for entities without a lineweight
+    pub line_width: f64,
+    /// Write a world file (`.pgw`/`.tfw`) next to the image
+    pub world_file: bool,
 }
 
 impl Default for RasterExportSettings {
@@ -430,12 +853,17 @@ impl Default for RasterExportSettings {
             background: Color::white(),
             anti_aliasing: 4,
             dpi: 96,
+            tile_size: 2048,
+            window: None,
+            line_width: 0.25,
+            world_file: false,
         }
     }
 }
 
 /// Raster image exporter
-/// Note: Actual rendering would require a graphics library
+/// Note: PNG and TIFF are rendered in tiles (see `io::raster`) and need the
+/// `native` feature; JPEG is not supported yet
 pub struct RasterExporter {
     settings: RasterExportSettings,
 }
@@ -447,14 +875,45 @@ impl RasterExporter {
     }
 
     /// Export to PNG
+    #[cfg(feature = "native")]
+    pub fn export_png<P: AsRef<Path>>(&self, doc: &Document, path: P) -> ExportResult<()> {
+        self.export_raster(doc, path, RasterFormat::Png).map(|_| ())
+    }
+
+    /// Export to PNG
+    #[cfg(not(feature = "native"))]
     pub fn export_png<P: AsRef<Path>>(&self, _doc: &Document, _path: P) -> ExportResult<()> {
-        // This would require implementing actual rasterization
-        // Using the image crate or similar
         Err(ExportError::Rendering(
-            "PNG export requires rendering implementation".to_string(),
+            "PNG export requires the native feature".to_string(),
+        ))
+    }
+
+    /// Export to TIFF
+    #[cfg(feature = "native")]
+    pub fn export_tiff<P: AsRef<Path>>(&self, doc: &Document, path: P) -> ExportResult<()> {
+        self.export_raster(doc, path, RasterFormat::Tiff).map(|_| ())
+    }
+
+    /// Export to TIFF
+    #[cfg(not(feature = "native"))]
+    pub fn export_tiff<P: AsRef<Path>>(&self, _doc: &Document, _path: P) -> ExportResult<()> {
+        Err(ExportError::Rendering(
+            "TIFF export requires the native feature".to_string(),
         ))
     }
 
+    /// Render in tiles and stream to an image file, returning the image
+    /// geometry (and world file, when enabled)
+    #[cfg(feature = "native")]
+    pub fn export_raster<P: AsRef<Path>>(
+        &self,
+        doc: &Document,
+        path: P,
+        format: RasterFormat,
+    ) -> ExportResult<RasterOutput> {
+        crate::io::raster::export_raster(doc, &self.settings, path, format)
+    }
+
     /// Export to JPEG
     pub fn export_jpeg<P: AsRef<Path>>(
         &self,
@@ -487,13 +946,17 @@ impl Exporter {
                 exporter.export(doc, path)
             }
             "pdf" => {
-                let exporter = PdfExporter::new(PdfExportSettings::default());
+                let exporter = PdfExporter::new(PdfExportSettings::for_document(doc));
                 exporter.export(doc, path)
             }
             "png" => {
                 let exporter = RasterExporter::new(RasterExportSettings::default());
                 exporter.export_png(doc, path)
             }
+            "tif" | "tiff" => {
+                let exporter = RasterExporter::new(RasterExportSettings::default());
+                exporter.export_tiff(doc, path)
+            }
             "jpg" | "jpeg" => {
                 let exporter = RasterExporter::new(RasterExportSettings::default());
                 exporter.export_jpeg(doc, path, 90)
@@ -511,6 +974,7 @@ impl Exporter {
             ("svg", "Scalable Vector Graphics"),
             ("pdf", "Portable Document Format"),
             ("png", "Portable Network Graphics"),
+            ("tif", "Tagged Image File Format"),
             ("jpg", "JPEG Image"),
         ]
     }
@@ -620,4 +1084,171 @@ mod tests {
         assert_eq!(exporter.escape_xml("A & B"), "A &amp; B");
         assert_eq!(exporter.escape_xml("<tag>"), "&lt;tag&gt;");
     }
+
+    fn export_svg(doc: &Document, settings: SvgExportSettings) -> String {
+        let mut buffer = Vec::new();
+        SvgExporter::new(settings)
+            .export_to_writer(doc, &mut buffer)
+            .unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    fn line_on(layer: &str) -> Entity {
+        Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::new(0.0, 0.0, 0.0),
+                end: Vec3::new(10.0, 5.0, 0.0),
+            }),
+            layer.to_string(),
+        )
+    }
+
+    fn hatch(pattern: &str) -> Entity {
+        Entity::new(
+            GeometryType::Hatch(Hatch {
+                scale: 2.0,
+                ..Hatch::new(
+                    pattern,
+                    vec![vec![
+                        Vec3::new(0.0, 0.0, 0.0),
+                        Vec3::new(10.0, 0.0, 0.0),
+                        Vec3::new(10.0, 10.0, 0.0),
+                    ]],
+                )
+            }),
+            "0".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_svg_layer_classes_and_dashes() {
+        let mut doc = Document::new();
+        doc.add_layer(Layer {
+            name: "A-WALL EXT".to_string(),
+            color: Color::red(),
+            line_type: LineType::Dashed,
+            line_weight: LineWeight::Default,
+            visible: true,
+            locked: false,
+            frozen: false,
+            plottable: true,
+            confidential: false,
+        });
+        doc.add_entity(line_on("A-WALL EXT"));
+        let mut over = line_on("A-WALL EXT");
+        over.color = Some(Color::blue());
+        over.line_type = Some(LineType::DashDot);
+        doc.add_entity(over);
+
+        let svg = export_svg(&doc, SvgExportSettings::default());
+        assert!(svg.contains("<style>"));
+        assert!(svg.contains(
+            ".layer-A-WALL_20_EXT { stroke: rgb(255,0,0); stroke-dasharray: 0.5,0.25; }"
+        ));
+        assert!(svg.contains(".layer .lt-dashdot { stroke-dasharray: 0.5,0.25,0,0.25; }"));
+        assert!(svg.contains(
+            "<g id=\"layer-A-WALL_20_EXT\" class=\"layer layer-A-WALL_20_EXT\" data-layer=\"A-WALL EXT\">"
+        ));
+        // ByLayer entities carry no presentation attributes
+        assert!(svg.contains("<line class=\"line\" x1=\"0\" y1=\"0\" x2=\"10\" y2=\"-5\" />"));
+        assert!(svg.contains("class=\"line lt-dashdot\""));
+        assert!(svg.contains("style=\"stroke:rgb(0,0,255)\""));
+
+        let settings = SvgExportSettings {
+            css_classes: false,
+            linetype_scale: 2.0,
+            ..Default::default()
+        };
+        let svg = export_svg(&doc, settings);
+        assert!(!svg.contains("<style>"));
+        assert!(svg.contains("stroke=\"rgb(255,0,0)\" fill=\"none\" stroke-width=\"1\" stroke-dasharray=\"1,0.5\""));
+        assert!(svg.contains("stroke-dasharray=\"1,0.5,0,0.5\""));
+    }
+
+    #[test]
+    fn test_svg_hatch_patterns() {
+        let mut doc = Document::new();
+        doc.add_entity(hatch("ansi31"));
+        doc.add_entity(hatch("ANSI31"));
+        doc.add_entity(hatch("SOLID"));
+
+        let svg = export_svg(&doc, SvgExportSettings::default());
+        assert_eq!(svg.matches("<pattern ").count(), 1);
+        assert!(svg.contains("<pattern id=\"hatch-0\" patternUnits=\"userSpaceOnUse\" width=\"6.35\""));
+        assert!(svg.contains("patternTransform=\"rotate(-45)\""));
+        assert_eq!(svg.matches("style=\"fill:url(#hatch-0);stroke:none\"").count(), 2);
+        assert!(svg.contains("style=\"fill:rgb(255,255,255);stroke:none\""));
+        assert!(svg.contains("d=\"M 0 0 L 10 0 L 10 -10 Z\" fill-rule=\"evenodd\""));
+
+        let settings = SvgExportSettings {
+            hatch_fills: false,
+            ..Default::default()
+        };
+        let svg = export_svg(&doc, settings);
+        assert!(!svg.contains("<pattern"));
+        assert!(!svg.contains("fill-rule"));
+    }
+
+    #[test]
+    fn test_svg_text_elements() {
+        let mut doc = Document::new();
+        doc.add_entity(Entity::new(
+            GeometryType::Text(Text {
+                position: Vec3::new(5.0, 5.0, 0.0),
+                text: "A & B".to_string(),
+                height: 2.5,
+                rotation: 0.0,
+                style: "ROMANS".to_string(),
+                horizontal_alignment: TextAlignment::Center,
+                vertical_alignment: TextAlignment::Bottom,
+            }),
+            "0".to_string(),
+        ));
+        doc.add_entity(Entity::new(
+            GeometryType::MText(MText {
+                position: Vec3::new(0.0, 20.0, 0.0),
+                text: "First\\PSecond".to_string(),
+                height: 2.0,
+                width: 50.0,
+                rotation: 0.0,
+                style: "Standard".to_string(),
+                line_spacing: 1.0,
+            }),
+            "0".to_string(),
+        ));
+
+        let mut settings = SvgExportSettings::default();
+        settings
+            .font_map
+            .insert("ROMANS".to_string(), "'Roman Sans'".to_string());
+        let svg = export_svg(&doc, settings);
+        assert!(svg.contains(".layer text { font-family: Arial, Helvetica, sans-serif; }"));
+        assert!(svg.contains("text-anchor=\"middle\""));
+        assert!(svg.contains(
+            "style=\"font-family:&apos;Roman Sans&apos;, Arial, Helvetica, sans-serif\">A &amp; B</text>"
+        ));
+        assert!(svg.contains("<tspan x=\"0\" dy=\"0\">First</tspan>"));
+        assert!(svg.contains("<tspan x=\"0\" dy=\"3\">Second</tspan>"));
+
+        let settings = SvgExportSettings {
+            css_classes: false,
+            ..Default::default()
+        };
+        let svg = export_svg(&doc, settings);
+        assert!(svg.contains("font-family=\"&apos;ROMANS&apos;, Arial, Helvetica, sans-serif\""));
+        assert!(svg.contains("font-family=\"Arial, Helvetica, sans-serif\""));
+    }
+
+    #[test]
+    fn test_coordinate_precision() {
+        let exporter = SvgExporter::new(SvgExportSettings {
+            precision: 2,
+            ..Default::default()
+        });
+        assert_eq!(exporter.fmt(1.23456), "1.23");
+        assert_eq!(exporter.fmt(1.5), "1.5");
+        assert_eq!(exporter.fmt(2.0), "2");
+        assert_eq!(exporter.fmt(-0.001), "0");
+        assert_eq!(exporter.fmt(-12.0), "-12");
+    }
 }
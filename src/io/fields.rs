@@ -0,0 +1,588 @@
+// CADDY - Enterprise CAD System
+// File I/O System - Text Fields Module
+
+//! Dynamic text fields
+//!
+//! Text, multiline text and block attribute values can embed fields, like
+//! AutoCAD fields: `{{NAME}}` or `{{NAME:format}}`. The field code stays in
+//! the drawing and is evaluated each time the text is displayed or plotted,
+//! so a title block always shows the current file name, save date,
+//! revision and sheet number without anyone editing it.
+//!
+//! Built-in fields read the document and the [`FieldContext`]:
+//!
+//! | Field | Value |
+//! |-------|-------|
+//! | `FILENAME`, `FILEPATH` | File the drawing was opened from |
+//! | `TITLE`, `AUTHOR`, `COMPANY`, `SUBJECT`, `COMMENTS`, `KEYWORDS` | Document metadata |
+//! | `CREATEDATE`, `SAVEDATE` | Document creation and last save dates |
+//! | `DATE`, `PLOTDATE` | Today, and the time of the current plot |
+//! | `REVISION` | The sheet's `REVISION` field or document property |
+//! | `SHEET_NUMBER`, `SHEET_TITLE`, `SHEET_COUNT`, `SHEET_SET`, `SUBSET` | Sheet set fields |
+//!
+//! Properties are reached by prefix: `PROP.<name>` for document custom
+//! properties, `VAR.<name>` for document variables, `PARAM.<name>` for
+//! document parameters (formatted in their unit), `SHEET.<name>`,
+//! `PROJECT.<name>` and `TENANT.<name>` for fields supplied by the sheet
+//! set, project and tenant. Any other name is looked up in those in turn.
+//! Names are case-insensitive.
+//!
+//! Date fields take a `strftime` format (`{{SAVEDATE:%d %b %Y}}`, default
+//! the document's regional date format, `%Y-%m-%d` unless set otherwise);
+//! text fields take `upper`, `lower` or `title`, and parameter
+//! fields a number of decimals (`{{PARAM.WIDTH:0}}`). A field with
+//! no value shows as `####`, as in AutoCAD, so missing data is visible on
+//! the plot.
+//!
+//! ## Example
+//!
+//! ```
+//! use caddy::io::document::Document;
+//! use caddy::io::fields::{evaluate, FieldContext};
+//!
+//! let mut doc = Document::new();
+//! doc.metadata.custom_properties.insert("REVISION".to_string(), "C".to_string());
+//!
+//! let context = FieldContext::new()
+//!     .with_file("projects/clinic/A-101.cdy")
+//!     .with_project([("CLIENT", "Riverside Health")]);
+//! let text = evaluate("{{FILENAME}} rev {{revision}} for {{PROJECT.CLIENT:upper}}", &doc, &context);
+//! assert_eq!(text, "A-101.cdy rev C for RIVERSIDE HEALTH");
+//! ```
+
+use crate::io::document::{Document, GeometryType};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Shown in place of a field that has no value
+pub const UNRESOLVED: &str = "####";
+
+/// Default format of date fields
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Sheet field or document property holding the drawing's revision
+pub const REVISION_PROPERTY: &str = "REVISION";
+
+/// What a field reads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldSource {
+    FileName,
+    FilePath,
+    Title,
+    Author,
+    Company,
+    Subject,
+    Comments,
+    Keywords,
+    CreateDate,
+    SaveDate,
+    Date,
+    PlotDate,
+    Revision,
+    /// Document custom property
+    Property(String),
+    /// Document variable
+    Variable(String),
+    /// Document parameter, evaluated
+    Parameter(String),
+    /// Sheet set field
+    Sheet(String),
+    /// Project property
+    Project(String),
+    /// Tenant property
+    Tenant(String),
+    /// Sheet field, document property, variable or parameter, project or tenant
+    /// property, whichever has it first
+    Named(String),
+}
+
+/// A field code: what to read and how to format it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub source: FieldSource,
+    pub format: Option<String>,
+}
+
+impl Field {
+    /// Parse the inside of `{{...}}`; `None` if it is empty
+    pub fn parse(code: &str) -> Option<Self> {
+        let (name, format) = match code.split_once(':') {
+            Some((name, format)) => (name.trim(), Some(format.trim().to_string())),
+            None => (code.trim(), None),
+        };
+        if name.is_empty() {
+            return None;
+        }
+
+        let source = match name.split_once('.') {
+            Some((prefix, key)) if !key.is_empty() => {
+                let key = key.to_string();
+                match prefix.to_ascii_uppercase().as_str() {
+                    "PROP" => FieldSource::Property(key),
+                    "VAR" => FieldSource::Variable(key),
+                    "PARAM" => FieldSource::Parameter(key),
+                    "SHEET" => FieldSource::Sheet(key),
+                    "PROJECT" => FieldSource::Project(key),
+                    "TENANT" => FieldSource::Tenant(key),
+                    _ => FieldSource::Named(name.to_string()),
+                }
+            }
+            _ => match name.to_ascii_uppercase().as_str() {
+                "FILENAME" => FieldSource::FileName,
+                "FILEPATH" => FieldSource::FilePath,
+                "TITLE" => FieldSource::Title,
+                "AUTHOR" => FieldSource::Author,
+                "COMPANY" => FieldSource::Company,
+                "SUBJECT" => FieldSource::Subject,
+                "COMMENTS" => FieldSource::Comments,
+                "KEYWORDS" => FieldSource::Keywords,
+                "CREATEDATE" => FieldSource::CreateDate,
+                "SAVEDATE" => FieldSource::SaveDate,
+                "DATE" => FieldSource::Date,
+                "PLOTDATE" => FieldSource::PlotDate,
+                "REVISION" => FieldSource::Revision,
+                _ => FieldSource::Named(name.to_string()),
+            },
+        };
+        Some(Self {
+            source,
+            format: format.filter(|f| !f.is_empty()),
+        })
+    }
+
+    /// Value of the field, formatted; `None` if it has none
+    pub fn resolve(&self, doc: &Document, context: &FieldContext) -> Option<String> {
+        let meta = &doc.metadata;
+        let text = |value: &str| (!value.is_empty()).then(|| value.to_string());
+        let value = match &self.source {
+            FieldSource::FileName => context
+                .file_path
+                .as_deref()
+                .and_then(Path::file_name)
+                .map(|name| name.to_string_lossy().into_owned()),
+            FieldSource::FilePath => context.file_path.as_ref().map(|p| p.display().to_string()),
+            FieldSource::Title => text(&meta.title),
+            FieldSource::Author => text(&meta.author),
+            FieldSource::Company => text(&meta.company),
+            FieldSource::Subject => text(&meta.subject),
+            FieldSource::Comments => text(&meta.comments),
+            FieldSource::Keywords => text(&meta.keywords.join(", ")),
+            FieldSource::CreateDate => return self.date(doc, meta.created),
+            FieldSource::SaveDate => return self.date(doc, meta.modified),
+            FieldSource::Date => return self.date(doc, context.now),
+            FieldSource::PlotDate => return context.plot_date.and_then(|at| self.date(doc, at)),
+            FieldSource::Revision => {
+                lookup(&context.sheet, REVISION_PROPERTY).or_else(|| lookup(&meta.custom_properties, REVISION_PROPERTY))
+            }
+            FieldSource::Property(key) => lookup(&meta.custom_properties, key),
+            FieldSource::Variable(key) => lookup(&doc.variables, key),
+            FieldSource::Parameter(key) => self.parameter(doc, key),
+            FieldSource::Sheet(key) => lookup(&context.sheet, key),
+            FieldSource::Project(key) => lookup(&context.project, key),
+            FieldSource::Tenant(key) => lookup(&context.tenant, key),
+            FieldSource::Named(key) => lookup(&context.sheet, key)
+                .or_else(|| lookup(&meta.custom_properties, key))
+                .or_else(|| lookup(&doc.variables, key))
+                .or_else(|| self.parameter(doc, key))
+                .or_else(|| lookup(&context.project, key))
+                .or_else(|| lookup(&context.tenant, key)),
+        }?;
+        Some(match self.format.as_deref().map(str::to_ascii_lowercase).as_deref() {
+            Some("upper") => value.to_uppercase(),
+            Some("lower") => value.to_lowercase(),
+            Some("title") => title_case(&value),
+            _ => value,
+        })
+    }
+
+    /// Parameter value in its unit; a numeric format is the number of decimals
+    fn parameter(&self, doc: &Document, key: &str) -> Option<String> {
+        let parameter = doc.parameters.iter().find(|p| p.name.eq_ignore_ascii_case(key))?;
+        let values = doc.parameters.evaluate(doc.settings.units).ok()?;
+        let precision = self.format.as_deref().and_then(|f| f.parse().ok());
+        values.format(&parameter.name, precision)
+    }
+
+    /// Date in the field's format, or the document's regional date format
+    fn date(&self, doc: &Document, at: DateTime<Utc>) -> Option<String> {
+        let regional = doc.settings.regional.date_format.as_str();
+        let format = match self.format.as_deref() {
+            Some(format) => format,
+            None if !regional.is_empty() => regional,
+            None => DEFAULT_DATE_FORMAT,
+        };
+        // Formatting an invalid specifier panics, so check it up front
+        let items: Vec<Item> = StrftimeItems::new(format).collect();
+        if items.iter().any(|item| matches!(item, Item::Error)) {
+            return None;
+        }
+        Some(at.format_with_items(items.into_iter()).to_string())
+    }
+}
+
+/// Values fields read from outside the document
+#[derive(Debug, Clone)]
+pub struct FieldContext {
+    /// File the drawing was opened from
+    pub file_path: Option<PathBuf>,
+    /// Time `DATE` fields show
+    pub now: DateTime<Utc>,
+    /// Time of the plot being made, if plotting
+    pub plot_date: Option<DateTime<Utc>>,
+    /// Sheet set fields for the sheet being shown
+    pub sheet: BTreeMap<String, String>,
+    /// Project properties
+    pub project: BTreeMap<String, String>,
+    /// Tenant properties
+    pub tenant: BTreeMap<String, String>,
+}
+
+impl Default for FieldContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FieldContext {
+    /// Context for display at the current time
+    pub fn new() -> Self {
+        Self {
+            file_path: None,
+            now: Utc::now(),
+            plot_date: None,
+            sheet: BTreeMap::new(),
+            project: BTreeMap::new(),
+            tenant: BTreeMap::new(),
+        }
+    }
+
+    /// Context for a plot made now
+    pub fn plot() -> Self {
+        let context = Self::new();
+        Self {
+            plot_date: Some(context.now),
+            ..context
+        }
+    }
+
+    /// Evaluate dates as of `now`
+    pub fn at(mut self, now: DateTime<Utc>) -> Self {
+        self.now = now;
+        if self.plot_date.is_some() {
+            self.plot_date = Some(now);
+        }
+        self
+    }
+
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file_path = Some(path.into());
+        self
+    }
+
+    pub fn with_sheet<K: Into<String>, V: Into<String>>(mut self, fields: impl IntoIterator<Item = (K, V)>) -> Self {
+        self.sheet.extend(fields.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    pub fn with_project<K: Into<String>, V: Into<String>>(mut self, fields: impl IntoIterator<Item = (K, V)>) -> Self {
+        self.project
+            .extend(fields.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    pub fn with_tenant<K: Into<String>, V: Into<String>>(mut self, fields: impl IntoIterator<Item = (K, V)>) -> Self {
+        self.tenant
+            .extend(fields.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+}
+
+/// Whether `text` contains any field
+pub fn has_fields(text: &str) -> bool {
+    !fields(text).is_empty()
+}
+
+/// Fields in `text`, in order
+pub fn fields(text: &str) -> Vec<Field> {
+    let mut found = Vec::new();
+    scan(text, |code| {
+        if let Some(field) = Field::parse(code) {
+            found.push(field);
+        }
+        None
+    });
+    found
+}
+
+/// `text` with every field replaced by its value
+///
+/// Borrows `text` unchanged when it has no fields.
+pub fn evaluate<'a>(text: &'a str, doc: &Document, context: &FieldContext) -> Cow<'a, str> {
+    if !text.contains("{{") {
+        return Cow::Borrowed(text);
+    }
+    scan(text, |code| {
+        let field = Field::parse(code)?;
+        Some(field.resolve(doc, context).unwrap_or_else(|| UNRESOLVED.to_string()))
+    })
+}
+
+/// Replace fields with their values throughout a drawing
+///
+/// For copies about to leave the application (published sheets, exports to
+/// formats without fields), where the values must be fixed. Text, multiline
+/// text and insert attributes are evaluated, in the drawing and its blocks.
+/// Returns the number of entities changed.
+pub fn evaluate_document(doc: &mut Document, context: &FieldContext) -> usize {
+    // Field values only read metadata and variables, so the geometry can be
+    // lifted out while it is rewritten
+    let mut entities = std::mem::take(&mut doc.entities);
+    let mut blocks = std::mem::take(&mut doc.blocks);
+    let mut changed = 0;
+    for entity in entities
+        .iter_mut()
+        .chain(blocks.values_mut().flat_map(|b| &mut b.entities))
+    {
+        let mut hit = false;
+        let mut apply = |text: &mut String| {
+            if let Cow::Owned(value) = evaluate(text, doc, context) {
+                *text = value;
+                hit = true;
+            }
+        };
+        match &mut entity.geometry {
+            GeometryType::Text(t) => apply(&mut t.text),
+            GeometryType::MText(t) => apply(&mut t.text),
+            GeometryType::Insert(insert) => insert.attributes.values_mut().for_each(apply),
+            _ => {}
+        }
+        changed += usize::from(hit);
+    }
+    doc.entities = entities;
+    doc.blocks = blocks;
+    changed
+}
+
+/// Top-level entities whose displayed text depends on fields
+///
+/// Includes inserts of blocks containing fields. These are the entities to
+/// redraw when metadata, the file name or sheet fields change.
+pub fn entities_with_fields(doc: &Document) -> Vec<Uuid> {
+    let geometry_has_fields = |geometry: &GeometryType| match geometry {
+        GeometryType::Text(t) => has_fields(&t.text),
+        GeometryType::MText(t) => has_fields(&t.text),
+        GeometryType::Insert(insert) => insert.attributes.values().any(|v| has_fields(v)),
+        _ => false,
+    };
+    let mut blocks_with_fields: Vec<&str> = doc
+        .blocks
+        .values()
+        .filter(|b| b.entities.iter().any(|e| geometry_has_fields(&e.geometry)))
+        .map(|b| b.name.as_str())
+        .collect();
+
+    // A block inserting a block with fields has fields too
+    loop {
+        let before = blocks_with_fields.len();
+        for block in doc.blocks.values() {
+            if blocks_with_fields.contains(&block.name.as_str()) {
+                continue;
+            }
+            let nested = block.entities.iter().any(|e| {
+                matches!(&e.geometry, GeometryType::Insert(i) if blocks_with_fields.contains(&i.block_name.as_str()))
+            });
+            if nested {
+                blocks_with_fields.push(block.name.as_str());
+            }
+        }
+        if blocks_with_fields.len() == before {
+            break;
+        }
+    }
+
+    doc.entities
+        .iter()
+        .filter(|e| {
+            geometry_has_fields(&e.geometry)
+                || matches!(&e.geometry, GeometryType::Insert(i) if blocks_with_fields.contains(&i.block_name.as_str()))
+        })
+        .map(|e| e.id)
+        .collect()
+}
+
+/// Walk the `{{...}}` codes in `text`, replacing those `replace` returns a
+/// value for; unterminated codes are left as they are
+fn scan<'a>(text: &'a str, mut replace: impl FnMut(&str) -> Option<String>) -> Cow<'a, str> {
+    let mut out = String::new();
+    let mut rest = text;
+    let mut copied = 0;
+    while let Some(open) = rest.find("{{") {
+        let Some(close) = rest[open + 2..].find("}}") else {
+            break;
+        };
+        let code = &rest[open + 2..open + 2 + close];
+        let end = open + 2 + close + 2;
+        if let Some(value) = replace(code) {
+            let start = text.len() - rest.len();
+            out.push_str(&text[copied..start + open]);
+            out.push_str(&value);
+            copied = start + end;
+        }
+        rest = &rest[end..];
+    }
+    if copied == 0 {
+        return Cow::Borrowed(text);
+    }
+    out.push_str(&text[copied..]);
+    Cow::Owned(out)
+}
+
+fn lookup<'a, M>(map: &'a M, key: &str) -> Option<String>
+where
+    &'a M: IntoIterator<Item = (&'a String, &'a String)>,
+{
+    map.into_iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v.clone())
+}
+
+fn title_case(value: &str) -> String {
+    value
+        .split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{Block, Entity, Insert, Text, TextAlignment, Vec3};
+    use crate::io::units::Unit;
+    use crate::io::regional::Region;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn text_entity(text: &str) -> Entity {
+        Entity::new(
+            GeometryType::Text(Text {
+                position: Vec3::zero(),
+                text: text.to_string(),
+                height: 2.5,
+                rotation: 0.0,
+                style: "Standard".to_string(),
+                horizontal_alignment: TextAlignment::Left,
+                vertical_alignment: TextAlignment::Bottom,
+            }),
+            "0".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_fields_evaluate_from_document_and_context() {
+        let mut doc = Document::new();
+        doc.metadata.title = "Ground Floor Plan".to_string();
+        doc.metadata.modified = Utc.with_ymd_and_hms(2026, 3, 14, 9, 30, 0).unwrap();
+        doc.metadata
+            .custom_properties
+            .insert("Revision".to_string(), "B".to_string());
+        doc.variables.insert("DRAWN_BY".to_string(), "jk".to_string());
+        doc.parameters.set("bay", "7.5", Some(Unit::Meters)).unwrap();
+        doc.parameters.set("Bays", "4", None).unwrap();
+
+        let context = FieldContext::plot()
+            .at(Utc.with_ymd_and_hms(2026, 4, 1, 12, 0, 0).unwrap())
+            .with_file("/projects/clinic/A-101.cdy")
+            .with_sheet([("SHEET_NUMBER", "A-101"), ("SHEET_COUNT", "12")])
+            .with_tenant([("NAME", "Harbor Architects")]);
+
+        let value = |text: &str| evaluate(text, &doc, &context).into_owned();
+        assert_eq!(value("{{FILENAME}}"), "A-101.cdy");
+        assert_eq!(value("{{title:upper}}"), "GROUND FLOOR PLAN");
+        assert_eq!(value("Saved {{SAVEDATE:%d %b %Y}}"), "Saved 14 Mar 2026");
+        assert_eq!(value("{{PLOTDATE}}"), "2026-04-01");
+        assert_eq!(value("Rev {{REVISION}}"), "Rev B");
+        assert_eq!(value("{{sheet_number}} of {{SHEET_COUNT}}"), "A-101 of 12");
+        assert_eq!(value("{{drawn_by:upper}} / {{TENANT.name}}"), "JK / Harbor Architects");
+        assert_eq!(value("{{bays}} bays of {{PARAM.BAY:1}}"), "4 bays of 7.5 m");
+
+        // Missing values and bad formats show up on the sheet
+        assert_eq!(value("{{PROJECT.CLIENT}} {{DATE:%Q}} {{AUTHOR}}"), "#### #### ####");
+        assert_eq!(value("{{}} and {{unterminated"), "{{}} and {{unterminated");
+        assert!(matches!(evaluate("plain", &doc, &context), Cow::Borrowed(_)));
+
+        // The sheet's revision wins over the document's
+        let context = context.with_sheet([("REVISION", "C")]);
+        assert_eq!(evaluate("{{REVISION}}", &doc, &context), "C");
+        // Display has no plot date
+        assert_eq!(evaluate("{{PLOTDATE}}", &doc, &FieldContext::new()), UNRESOLVED);
+
+        // Dates default to the document's regional format
+        Region::Germany.preset().apply(&mut doc.settings);
+        assert_eq!(evaluate("{{PLOTDATE}}", &doc, &context), "01.04.2026");
+    }
+
+    #[test]
+    fn test_entities_with_fields_and_baking() {
+        let mut doc = Document::new();
+        doc.metadata.title = "Site Plan".to_string();
+        doc.add_block(Block {
+            name: "TB".to_string(),
+            base_point: Vec3::zero(),
+            entities: vec![text_entity("{{TITLE}}")],
+            description: String::new(),
+        });
+        let mut attributes = HashMap::new();
+        attributes.insert("DWG".to_string(), "{{FILENAME}}".to_string());
+        let insert = Entity::new(
+            GeometryType::Insert(Insert {
+                block_name: "TB".to_string(),
+                position: Vec3::zero(),
+                scale: Vec3::new(1.0, 1.0, 1.0),
+                rotation: 0.0,
+                attributes: HashMap::new(),
+            }),
+            "0".to_string(),
+        );
+        let insert_id = insert.id;
+        let plain = text_entity("No fields here");
+        let with_attribute = Entity::new(
+            GeometryType::Insert(Insert {
+                block_name: "OTHER".to_string(),
+                position: Vec3::zero(),
+                scale: Vec3::new(1.0, 1.0, 1.0),
+                rotation: 0.0,
+                attributes,
+            }),
+            "0".to_string(),
+        );
+        let attribute_id = with_attribute.id;
+        doc.add_entity(insert);
+        doc.add_entity(plain);
+        doc.add_entity(with_attribute);
+
+        assert_eq!(entities_with_fields(&doc), vec![insert_id, attribute_id]);
+
+        let context = FieldContext::new().with_file("site.cdy");
+        assert_eq!(evaluate_document(&mut doc, &context), 2);
+        let GeometryType::Text(title) = &doc.blocks["TB"].entities[0].geometry else {
+            panic!("expected text");
+        };
+        assert_eq!(title.text, "Site Plan");
+        let GeometryType::Insert(insert) = &doc.entities[2].geometry else {
+            panic!("expected insert");
+        };
+        assert_eq!(insert.attributes["DWG"], "site.cdy");
+        assert!(entities_with_fields(&doc).is_empty());
+    }
+}
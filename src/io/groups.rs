@@ -0,0 +1,400 @@
+// CADDY - Enterprise CAD System
+// File I/O System - Groups and Named Selections Module
+
+//! Named groups and selection sets
+//!
+//! A [`Group`] is a named collection of entities that is picked as a unit:
+//! picking any member selects the whole group ([`expand_selection`]).
+//! Groups nest, and a group can be made unselectable for a while to edit
+//! its members one by one. A [`NamedSelection`] is a saved list of
+//! entities with no picking behavior of its own, kept so a selection can be
+//! recalled later.
+//!
+//! Both are saved in the document, in [`Document::groups`] and
+//! [`Document::selections`], and forget entities as they are removed.
+//! Scripts and plugins look sets up by name with [`resolve`], which also
+//! runs saved queries, so "grid lines" or "phase 1" can be a group, a
+//! selection or a query without the caller knowing which.
+//!
+//! ## Example
+//! ```
+//! use caddy::io::document::{Document, Entity, GeometryType, Point, Vec3};
+//! use caddy::io::groups;
+//!
+//! let mut doc = Document::new();
+//! let point = |x| {
+//!     let position = Vec3::new(x, 0.0, 0.0);
+//!     Entity::new(GeometryType::Point(Point { position }), "0".to_string())
+//! };
+//! let a = doc.add_entity(point(0.0));
+//! let b = doc.add_entity(point(1.0));
+//!
+//! groups::create_group(&mut doc, "Grid lines", &[a, b]).unwrap();
+//! assert_eq!(groups::expand_selection(&doc, &[a]), vec![a, b]);
+//!
+//! groups::set_selectable(&mut doc, "Grid lines", false).unwrap();
+//! assert_eq!(groups::expand_selection(&doc, &[a]), vec![a]);
+//! assert_eq!(groups::resolve(&doc, "Grid lines").unwrap(), vec![a, b]);
+//! ```
+
+use crate::io::document::Document;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Group and selection set errors
+#[derive(Error, Debug)]
+pub enum GroupError {
+    #[error("Invalid name '{0}'")]
+    InvalidName(String),
+    #[error("A group named '{0}' already exists")]
+    DuplicateGroup(String),
+    #[error("No group named '{0}'")]
+    UnknownGroup(String),
+    #[error("No group, selection or query named '{0}'")]
+    UnknownSet(String),
+    #[error("Group '{child}' contains '{parent}' and cannot be nested in it")]
+    Cycle { parent: String, child: String },
+}
+
+pub type GroupResult<T> = Result<T, GroupError>;
+
+/// Entities picked as a unit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Group {
+    /// What the group is for
+    #[serde(default)]
+    pub description: String,
+    /// Member entities, in the order they were added
+    #[serde(default)]
+    pub entities: Vec<Uuid>,
+    /// Names of nested groups
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Whether picking a member selects the group; turned off to pick
+    /// members on their own
+    #[serde(default = "selectable_default")]
+    pub selectable: bool,
+}
+
+fn selectable_default() -> bool {
+    true
+}
+
+impl Group {
+    /// Selectable group of entities
+    pub fn new(entities: &[Uuid]) -> Self {
+        Self {
+            description: String::new(),
+            entities: entities.to_vec(),
+            groups: Vec::new(),
+            selectable: true,
+        }
+    }
+}
+
+/// A saved selection, recalled by name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NamedSelection {
+    /// What the selection is for
+    #[serde(default)]
+    pub description: String,
+    /// Selected entities, in selection order
+    #[serde(default)]
+    pub entities: Vec<Uuid>,
+}
+
+fn check_name(name: &str) -> GroupResult<&str> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err(GroupError::InvalidName(name.to_string()));
+    }
+    Ok(trimmed)
+}
+
+/// Group entities under a new name
+pub fn create_group(doc: &mut Document, name: &str, entities: &[Uuid]) -> GroupResult<()> {
+    let name = check_name(name)?;
+    if doc.groups.contains_key(name) {
+        return Err(GroupError::DuplicateGroup(name.to_string()));
+    }
+    doc.groups.insert(name.to_string(), Group::new(entities));
+    Ok(())
+}
+
+fn group_mut<'a>(doc: &'a mut Document, name: &str) -> GroupResult<&'a mut Group> {
+    doc.groups
+        .get_mut(name)
+        .ok_or_else(|| GroupError::UnknownGroup(name.to_string()))
+}
+
+/// Add entities to a group; ones already in it are skipped
+pub fn add_to_group(doc: &mut Document, name: &str, entities: &[Uuid]) -> GroupResult<()> {
+    let group = group_mut(doc, name)?;
+    for &id in entities {
+        if !group.entities.contains(&id) {
+            group.entities.push(id);
+        }
+    }
+    Ok(())
+}
+
+/// Take entities out of a group
+pub fn remove_from_group(doc: &mut Document, name: &str, entities: &[Uuid]) -> GroupResult<()> {
+    group_mut(doc, name)?.entities.retain(|id| !entities.contains(id));
+    Ok(())
+}
+
+/// Nest group `child` in group `parent`
+///
+/// A group may not end up inside itself, directly or through other groups.
+pub fn nest_group(doc: &mut Document, parent: &str, child: &str) -> GroupResult<()> {
+    if !doc.groups.contains_key(child) {
+        return Err(GroupError::UnknownGroup(child.to_string()));
+    }
+    if child == parent || nested_groups(doc, child).contains(parent) {
+        return Err(GroupError::Cycle {
+            parent: parent.to_string(),
+            child: child.to_string(),
+        });
+    }
+    let group = group_mut(doc, parent)?;
+    if !group.groups.iter().any(|g| g == child) {
+        group.groups.push(child.to_string());
+    }
+    Ok(())
+}
+
+/// Dissolve a group, leaving its entities and nested groups alone
+pub fn ungroup(doc: &mut Document, name: &str) -> GroupResult<Group> {
+    let group = doc
+        .groups
+        .remove(name)
+        .ok_or_else(|| GroupError::UnknownGroup(name.to_string()))?;
+    for parent in doc.groups.values_mut() {
+        parent.groups.retain(|g| g != name);
+    }
+    Ok(group)
+}
+
+/// Turn picking of a group as a unit on or off
+pub fn set_selectable(doc: &mut Document, name: &str, selectable: bool) -> GroupResult<()> {
+    group_mut(doc, name)?.selectable = selectable;
+    Ok(())
+}
+
+/// Names of the groups nested in `name`, at any depth
+fn nested_groups(doc: &Document, name: &str) -> HashSet<String> {
+    let mut found = HashSet::new();
+    let mut pending = vec![name.to_string()];
+    while let Some(next) = pending.pop() {
+        for child in doc.groups.get(&next).map(|g| g.groups.as_slice()).unwrap_or_default() {
+            if found.insert(child.clone()) {
+                pending.push(child.clone());
+            }
+        }
+    }
+    found
+}
+
+/// Entities of a group and the groups nested in it, without duplicates
+pub fn group_members(doc: &Document, name: &str) -> GroupResult<Vec<Uuid>> {
+    let group = doc
+        .groups
+        .get(name)
+        .ok_or_else(|| GroupError::UnknownGroup(name.to_string()))?;
+    let mut seen = HashSet::new();
+    let mut members = Vec::new();
+    let mut visited = HashSet::new();
+    let mut pending = vec![group];
+    while let Some(group) = pending.pop() {
+        members.extend(group.entities.iter().copied().filter(|id| seen.insert(*id)));
+        for child in group.groups.iter().rev() {
+            if visited.insert(child.as_str()) {
+                pending.extend(doc.groups.get(child));
+            }
+        }
+    }
+    Ok(members)
+}
+
+/// Names of the groups an entity is directly in, sorted
+pub fn groups_of(doc: &Document, id: Uuid) -> Vec<String> {
+    let mut names: Vec<String> = doc
+        .groups
+        .iter()
+        .filter(|(_, group)| group.entities.contains(&id))
+        .map(|(name, _)| name.clone())
+        .collect();
+    names.sort();
+    names
+}
+
+/// The outermost selectable group holding `name`, climbing through
+/// selectable parents only
+fn outermost(doc: &Document, name: &str) -> String {
+    let mut current = name.to_string();
+    let mut visited = HashSet::new();
+    while visited.insert(current.clone()) {
+        let parent = doc
+            .groups
+            .iter()
+            .filter(|(_, group)| group.selectable && group.groups.contains(&current))
+            .map(|(name, _)| name)
+            .min();
+        match parent {
+            Some(parent) => current = parent.clone(),
+            None => break,
+        }
+    }
+    current
+}
+
+/// Picked entities with the members of every selectable group they are in
+///
+/// Each picked entity brings in its outermost selectable group, so picking
+/// a bolt in a flange in an assembly selects the assembly. Picked entities
+/// come first, in order, followed by the group members they brought in.
+pub fn expand_selection(doc: &Document, picked: &[Uuid]) -> Vec<Uuid> {
+    let mut seen: HashSet<Uuid> = HashSet::new();
+    let mut selection: Vec<Uuid> = picked.iter().copied().filter(|id| seen.insert(*id)).collect();
+    let mut expanded = HashSet::new();
+    for id in picked {
+        for name in groups_of(doc, *id) {
+            if !doc.groups[&name].selectable {
+                continue;
+            }
+            let top = outermost(doc, &name);
+            if expanded.insert(top.clone()) {
+                let members = group_members(doc, &top).unwrap_or_default();
+                selection.extend(members.into_iter().filter(|id| seen.insert(*id)));
+            }
+        }
+    }
+    selection
+}
+
+/// Save a selection under a name, replacing any selection saved under it
+pub fn save_selection(doc: &mut Document, name: &str, entities: &[Uuid]) -> GroupResult<()> {
+    let name = check_name(name)?;
+    let selection = doc.selections.entry(name.to_string()).or_default();
+    selection.entities = entities.to_vec();
+    Ok(())
+}
+
+/// Forget a saved selection; returns whether there was one
+pub fn delete_selection(doc: &mut Document, name: &str) -> bool {
+    doc.selections.remove(name).is_some()
+}
+
+/// Entities of the group, saved selection or saved query with a name
+///
+/// Groups come first, then saved selections, then saved queries, so scripts
+/// and plugins can work on a set without knowing how it is kept.
+pub fn resolve(doc: &Document, name: &str) -> GroupResult<Vec<Uuid>> {
+    if doc.groups.contains_key(name) {
+        return group_members(doc, name);
+    }
+    if let Some(selection) = doc.selections.get(name) {
+        return Ok(selection.entities.clone());
+    }
+    match doc.queries.get(name) {
+        Some(query) => Ok(query.select(doc)),
+        None => Err(GroupError::UnknownSet(name.to_string())),
+    }
+}
+
+/// Names [`resolve`] accepts, sorted and without duplicates
+pub fn set_names(doc: &Document) -> Vec<String> {
+    let mut names: Vec<String> = doc
+        .groups
+        .keys()
+        .chain(doc.selections.keys())
+        .chain(doc.queries.keys())
+        .cloned()
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Drop a removed entity from every group and saved selection
+pub(crate) fn forget_entity(doc: &mut Document, id: Uuid) {
+    for group in doc.groups.values_mut() {
+        group.entities.retain(|member| *member != id);
+    }
+    for selection in doc.selections.values_mut() {
+        selection.entities.retain(|member| *member != id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{Entity, GeometryType, Point, Vec3};
+    use crate::io::tags::{self, EntityQuery};
+
+    fn points(doc: &mut Document, count: usize) -> Vec<Uuid> {
+        (0..count)
+            .map(|i| {
+                let point = Point { position: Vec3::new(i as f64, 0.0, 0.0) };
+                doc.add_entity(Entity::new(GeometryType::Point(point), "0".to_string()))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_nested_groups_pick_as_a_unit() {
+        let mut doc = Document::new();
+        let ids = points(&mut doc, 5);
+        create_group(&mut doc, "Bolts", &ids[0..2]).unwrap();
+        create_group(&mut doc, "Flange", &ids[2..3]).unwrap();
+        create_group(&mut doc, "Assembly", &[]).unwrap();
+        nest_group(&mut doc, "Flange", "Bolts").unwrap();
+        nest_group(&mut doc, "Assembly", "Flange").unwrap();
+        assert!(matches!(create_group(&mut doc, "Bolts", &[]), Err(GroupError::DuplicateGroup(_))));
+        assert!(matches!(nest_group(&mut doc, "Bolts", "Assembly"), Err(GroupError::Cycle { .. })));
+
+        // Picking a bolt selects everything up to the assembly
+        assert_eq!(expand_selection(&doc, &[ids[4], ids[0]]), vec![ids[4], ids[0], ids[2], ids[1]]);
+
+        // With the assembly turned off, picking stops at the flange
+        set_selectable(&mut doc, "Assembly", false).unwrap();
+        assert_eq!(expand_selection(&doc, &[ids[1]]), vec![ids[1], ids[2], ids[0]]);
+        set_selectable(&mut doc, "Flange", false).unwrap();
+        assert_eq!(expand_selection(&doc, &[ids[1]]), vec![ids[1], ids[0]]);
+        assert_eq!(expand_selection(&doc, &[ids[2]]), vec![ids[2]]);
+
+        // Removed entities leave their groups; ungrouping unlinks parents
+        doc.remove_entity(ids[0]);
+        assert_eq!(group_members(&doc, "Assembly").unwrap(), vec![ids[2], ids[1]]);
+        ungroup(&mut doc, "Flange").unwrap();
+        assert!(doc.groups["Assembly"].groups.is_empty());
+        assert_eq!(groups_of(&doc, ids[1]), vec!["Bolts".to_string()]);
+    }
+
+    #[test]
+    fn test_resolve_groups_selections_and_queries() {
+        let mut doc = Document::new();
+        let ids = points(&mut doc, 3);
+        tags::add_tag(&mut doc.entities[2], "phase-1").unwrap();
+        doc.queries.insert("Phase 1".to_string(), EntityQuery::new().tagged("phase-1"));
+        create_group(&mut doc, "Grid lines", &ids[0..2]).unwrap();
+        save_selection(&mut doc, " Last pick ", &[ids[1]]).unwrap();
+        assert!(matches!(save_selection(&mut doc, " ", &ids), Err(GroupError::InvalidName(_))));
+
+        assert_eq!(resolve(&doc, "Grid lines").unwrap(), ids[0..2].to_vec());
+        assert_eq!(resolve(&doc, "Last pick").unwrap(), vec![ids[1]]);
+        assert_eq!(resolve(&doc, "Phase 1").unwrap(), vec![ids[2]]);
+        assert!(matches!(resolve(&doc, "Phase 2"), Err(GroupError::UnknownSet(_))));
+        assert_eq!(set_names(&doc), vec!["Grid lines", "Last pick", "Phase 1"]);
+
+        // Groups and selections are saved with the document
+        let json = serde_json::to_string(&doc).unwrap();
+        let loaded: Document = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.groups, doc.groups);
+        assert_eq!(loaded.selections, doc.selections);
+        assert!(delete_selection(&mut doc, "Last pick"));
+    }
+}
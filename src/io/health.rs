@@ -0,0 +1,991 @@
+//! # Drawing Health
+//!
+//! Profiles a [`Document`] for the things that make drawings slow to open,
+//! slow to regenerate, or needlessly large:
+//!
+//! - Entity counts by type and by layer
+//! - Blocks whose flattened geometry, times their insert count, dominates
+//!   the drawing
+//! - Tessellation hotspots: entities that expand into the most line segments
+//!   at the display chord tolerance
+//! - Undo history memory, when the caller supplies it
+//! - Unused layers and block definitions
+//! - Proxy entities dropped on DXF import and references to missing blocks
+//!   or layers
+//!
+//! Each [`HealthIssue`] can carry a [`Remediation`], which fixes the problem
+//! in place ([`purge`], [`audit`], [`simplify_splines`]).
+//!
+//! ## Example
+//!
+//! ```
+//! use caddy::io::document::Document;
+//! use caddy::io::health::{HealthProfiler, HealthThresholds};
+//!
+//! let mut doc = Document::new();
+//! let report = HealthProfiler::new().profile(&doc);
+//! for remediation in report.remediations() {
+//!     remediation.apply(&mut doc, &HealthThresholds::default());
+//! }
+//! ```
+
+use crate::io::document::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::f64::consts::PI;
+use uuid::Uuid;
+
+/// Document variable the DXF reader sets to the number of proxy entities it
+/// could not read
+pub const PROXY_COUNT_VARIABLE: &str = "PROXYCOUNT";
+
+/// Upper bound on segments for a single curve, matching what the renderer
+/// will emit at extreme zoom
+const MAX_CURVE_SEGMENTS: usize = 4096;
+
+/// Segments per spline span used by the renderer
+const SPLINE_SPAN_SEGMENTS: usize = 16;
+
+/// Outline strokes per text character
+const GLYPH_SEGMENTS: usize = 8;
+
+/// Segments drawn for a dimension (lines, arrows, extension lines)
+const DIMENSION_SEGMENTS: usize = 12;
+
+/// Hatch pattern line spacing at scale 1, in drawing units
+const HATCH_SPACING: f64 = 3.175;
+
+/// Layers a purge must keep
+const RESERVED_LAYERS: [&str; 2] = ["0", "DEFPOINTS"];
+
+/// Limits that turn measurements into issues
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthThresholds {
+    /// Chord tolerance for tessellation estimates, in drawing units
+    pub chord_tolerance: f64,
+    /// Entries kept in the heaviest block and hotspot lists
+    pub top_n: usize,
+    /// Splines with more control points are flagged for simplification
+    pub spline_control_points: usize,
+    /// Serialized size above which the drawing is flagged
+    pub file_size_bytes: u64,
+    /// Share of the undo memory limit above which history is flagged
+    pub undo_memory_share: f64,
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        Self {
+            chord_tolerance: 0.01,
+            top_n: 10,
+            spline_control_points: 64,
+            file_size_bytes: 50 * 1024 * 1024,
+            undo_memory_share: 0.75,
+        }
+    }
+}
+
+/// How much a block definition costs the drawing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockWeight {
+    /// Block name
+    pub name: String,
+    /// Entities in the definition, nested blocks flattened
+    pub entities: usize,
+    /// Tessellated segments for one insert
+    pub segments: usize,
+    /// Inserts in model space
+    pub inserts: usize,
+}
+
+impl BlockWeight {
+    /// Segments contributed to model space
+    pub fn total_segments(&self) -> usize {
+        self.segments * self.inserts
+    }
+}
+
+/// An entity that is expensive to regenerate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TessellationHotspot {
+    /// Entity ID
+    pub entity_id: Uuid,
+    /// Entity type name
+    pub type_name: String,
+    /// Entity layer
+    pub layer: String,
+    /// Estimated line segments
+    pub segments: usize,
+}
+
+/// Undo history memory, as reported by the command history
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UndoUsage {
+    /// Estimated bytes held by undo and redo entries
+    pub bytes: usize,
+    /// Configured limit in bytes (0 = unlimited)
+    pub limit_bytes: usize,
+    /// Undo levels held
+    pub levels: usize,
+}
+
+/// One-click fix for a health issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Remediation {
+    /// Remove unused layers and block definitions
+    Purge,
+    /// Recreate missing layers and drop inserts of missing blocks
+    Audit,
+    /// Reduce spline control points
+    SimplifySplines,
+}
+
+impl Remediation {
+    /// Command that runs the fix
+    pub fn command(&self) -> &'static str {
+        match self {
+            Remediation::Purge => "PURGE",
+            Remediation::Audit => "AUDIT",
+            Remediation::SimplifySplines => "SIMPLIFY",
+        }
+    }
+
+    /// Button label
+    pub fn label(&self) -> &'static str {
+        match self {
+            Remediation::Purge => "Purge unused",
+            Remediation::Audit => "Audit references",
+            Remediation::SimplifySplines => "Simplify splines",
+        }
+    }
+
+    /// Apply the fix, returning the number of objects changed
+    pub fn apply(&self, doc: &mut Document, thresholds: &HealthThresholds) -> usize {
+        match self {
+            Remediation::Purge => {
+                let purged = purge(doc);
+                purged.layers.len() + purged.blocks.len()
+            }
+            Remediation::Audit => audit(doc),
+            Remediation::SimplifySplines => simplify_splines(
+                doc,
+                thresholds.spline_control_points,
+                thresholds.chord_tolerance,
+            ),
+        }
+    }
+}
+
+/// Issue severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum HealthSeverity {
+    /// Worth knowing
+    Info,
+    /// Hurts performance or reliability
+    Warning,
+}
+
+/// A problem found while profiling
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthIssue {
+    /// Severity
+    pub severity: HealthSeverity,
+    /// Description
+    pub message: String,
+    /// Fix, if one can be applied automatically
+    pub remediation: Option<Remediation>,
+}
+
+impl HealthIssue {
+    fn new(severity: HealthSeverity, message: String, remediation: Option<Remediation>) -> Self {
+        Self {
+            severity,
+            message,
+            remediation,
+        }
+    }
+}
+
+/// Result of profiling a document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// On-disk size if known, otherwise the native serialized size
+    pub file_size: u64,
+    /// Model space entities
+    pub entity_count: usize,
+    /// Model space entities by type name
+    pub by_type: BTreeMap<String, usize>,
+    /// Model space entities by layer
+    pub by_layer: BTreeMap<String, usize>,
+    /// Block definitions by segments contributed, heaviest first
+    pub heaviest_blocks: Vec<BlockWeight>,
+    /// Model space entities by segments, most expensive first
+    pub hotspots: Vec<TessellationHotspot>,
+    /// Estimated segments to draw model space
+    pub total_segments: usize,
+    /// Layers no entity uses
+    pub unused_layers: Vec<String>,
+    /// Block definitions never inserted
+    pub unused_blocks: Vec<String>,
+    /// Inserts of blocks that are not defined
+    pub unresolved_inserts: usize,
+    /// Entities on layers that are not defined
+    pub undefined_layer_entities: usize,
+    /// Splines over the control point threshold
+    pub complex_splines: usize,
+    /// Proxy entities dropped on import
+    pub proxy_count: usize,
+    /// Undo history memory, if supplied
+    pub undo: Option<UndoUsage>,
+    /// Problems found
+    pub issues: Vec<HealthIssue>,
+}
+
+impl HealthReport {
+    /// No warnings
+    pub fn is_healthy(&self) -> bool {
+        self.issues
+            .iter()
+            .all(|issue| issue.severity < HealthSeverity::Warning)
+    }
+
+    /// Distinct remediations offered by the issues, in issue order
+    pub fn remediations(&self) -> Vec<Remediation> {
+        let mut seen = HashSet::new();
+        self.issues
+            .iter()
+            .filter_map(|issue| issue.remediation)
+            .filter(|remediation| seen.insert(*remediation))
+            .collect()
+    }
+}
+
+/// Builds a [`HealthReport`]
+#[derive(Debug, Clone, Default)]
+pub struct HealthProfiler {
+    thresholds: HealthThresholds,
+    file_size: Option<u64>,
+    undo: Option<UndoUsage>,
+}
+
+impl HealthProfiler {
+    /// Profiler with default thresholds
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use custom thresholds
+    pub fn with_thresholds(mut self, thresholds: HealthThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Report the size of the file on disk instead of estimating it
+    pub fn with_file_size(mut self, bytes: u64) -> Self {
+        self.file_size = Some(bytes);
+        self
+    }
+
+    /// Include undo history memory
+    pub fn with_undo_history(mut self, bytes: usize, limit_bytes: usize, levels: usize) -> Self {
+        self.undo = Some(UndoUsage {
+            bytes,
+            limit_bytes,
+            levels,
+        });
+        self
+    }
+
+    /// Profile a document
+    pub fn profile(&self, doc: &Document) -> HealthReport {
+        let t = &self.thresholds;
+        let mut costs = BlockCosts::new(doc, t.chord_tolerance);
+
+        let mut by_type = BTreeMap::new();
+        let mut by_layer = BTreeMap::new();
+        let mut hotspots = Vec::new();
+        let mut total_segments = 0;
+        let mut inserts: HashMap<&str, usize> = HashMap::new();
+        let mut unresolved_inserts = 0;
+
+        for entity in &doc.entities {
+            *by_type
+                .entry(entity.geometry.type_name().to_string())
+                .or_insert(0) += 1;
+            *by_layer.entry(entity.layer.clone()).or_insert(0) += 1;
+
+            if let GeometryType::Insert(insert) = &entity.geometry {
+                if doc.blocks.contains_key(&insert.block_name) {
+                    *inserts.entry(insert.block_name.as_str()).or_insert(0) += 1;
+                } else {
+                    unresolved_inserts += 1;
+                }
+            }
+
+            let segments = costs.segments(&entity.geometry);
+            total_segments += segments;
+            hotspots.push(TessellationHotspot {
+                entity_id: entity.id,
+                type_name: entity.geometry.type_name().to_string(),
+                layer: entity.layer.clone(),
+                segments,
+            });
+        }
+        hotspots.sort_by_key(|hotspot| std::cmp::Reverse(hotspot.segments));
+        hotspots.truncate(t.top_n);
+
+        let mut heaviest_blocks: Vec<BlockWeight> = doc
+            .blocks
+            .keys()
+            .map(|name| {
+                let (entities, segments) = costs.block(name);
+                BlockWeight {
+                    name: name.clone(),
+                    entities,
+                    segments,
+                    inserts: inserts.get(name.as_str()).copied().unwrap_or(0),
+                }
+            })
+            .collect();
+        heaviest_blocks.sort_by(|a, b| {
+            b.total_segments()
+                .cmp(&a.total_segments())
+                .then(b.entities.cmp(&a.entities))
+                .then(a.name.cmp(&b.name))
+        });
+        heaviest_blocks.truncate(t.top_n);
+
+        let undefined_layer_entities = all_entities(doc)
+            .filter(|entity| !doc.layers.contains_key(&entity.layer))
+            .count();
+        let complex_splines = all_entities(doc)
+            .filter(|entity| match &entity.geometry {
+                GeometryType::Spline(spline) => {
+                    spline.control_points.len() > t.spline_control_points
+                }
+                _ => false,
+            })
+            .count();
+
+        let mut report = HealthReport {
+            file_size: self
+                .file_size
+                .unwrap_or_else(|| bincode::serialized_size(doc).unwrap_or(0)),
+            entity_count: doc.entities.len(),
+            by_type,
+            by_layer,
+            heaviest_blocks,
+            hotspots,
+            total_segments,
+            unused_layers: unused_layers(doc),
+            unused_blocks: unused_blocks(doc),
+            unresolved_inserts,
+            undefined_layer_entities,
+            complex_splines,
+            proxy_count: doc
+                .variables
+                .get(PROXY_COUNT_VARIABLE)
+                .and_then(|count| count.parse().ok())
+                .unwrap_or(0),
+            undo: self.undo,
+            issues: Vec::new(),
+        };
+        report.issues = self.issues(&report);
+        report
+    }
+
+    fn issues(&self, report: &HealthReport) -> Vec<HealthIssue> {
+        let t = &self.thresholds;
+        let mut issues = Vec::new();
+        let has_unused = !report.unused_layers.is_empty() || !report.unused_blocks.is_empty();
+
+        if report.file_size > t.file_size_bytes {
+            issues.push(HealthIssue::new(
+                HealthSeverity::Warning,
+                format!("Drawing is {}", format_bytes(report.file_size)),
+                has_unused.then_some(Remediation::Purge),
+            ));
+        }
+
+        if has_unused {
+            issues.push(HealthIssue::new(
+                HealthSeverity::Info,
+                format!(
+                    "{} unused layers and {} unused block definitions",
+                    report.unused_layers.len(),
+                    report.unused_blocks.len()
+                ),
+                Some(Remediation::Purge),
+            ));
+        }
+
+        if report.unresolved_inserts > 0 || report.undefined_layer_entities > 0 {
+            issues.push(HealthIssue::new(
+                HealthSeverity::Warning,
+                format!(
+                    "{} inserts of undefined blocks and {} entities on undefined layers",
+                    report.unresolved_inserts, report.undefined_layer_entities
+                ),
+                Some(Remediation::Audit),
+            ));
+        }
+
+        if report.complex_splines > 0 {
+            issues.push(HealthIssue::new(
+                HealthSeverity::Warning,
+                format!(
+                    "{} splines have more than {} control points",
+                    report.complex_splines, t.spline_control_points
+                ),
+                Some(Remediation::SimplifySplines),
+            ));
+        }
+
+        if report.proxy_count > 0 {
+            issues.push(HealthIssue::new(
+                HealthSeverity::Warning,
+                format!(
+                    "{} proxy entities were dropped on import; explode them in the authoring application and re-export",
+                    report.proxy_count
+                ),
+                None,
+            ));
+        }
+
+        if let Some(undo) = report.undo {
+            if undo.limit_bytes > 0
+                && undo.bytes as f64 > undo.limit_bytes as f64 * t.undo_memory_share
+            {
+                issues.push(HealthIssue::new(
+                    HealthSeverity::Warning,
+                    format!(
+                        "Undo history holds {} of its {} limit across {} levels",
+                        format_bytes(undo.bytes as u64),
+                        format_bytes(undo.limit_bytes as u64),
+                        undo.levels
+                    ),
+                    None,
+                ));
+            }
+        }
+
+        issues
+    }
+}
+
+/// What [`purge`] removed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PurgeSummary {
+    /// Removed layers
+    pub layers: Vec<String>,
+    /// Removed block definitions
+    pub blocks: Vec<String>,
+}
+
+/// Remove unused block definitions and layers
+///
+/// Blocks go first, so layers only used inside purged blocks are purged too.
+pub fn purge(doc: &mut Document) -> PurgeSummary {
+    let blocks = unused_blocks(doc);
+    for name in &blocks {
+        doc.blocks.remove(name);
+    }
+
+    let layers = unused_layers(doc);
+    for name in &layers {
+        doc.layers.remove(name);
+    }
+
+    if !blocks.is_empty() || !layers.is_empty() {
+        doc.metadata.modified = chrono::Utc::now();
+    }
+    PurgeSummary { layers, blocks }
+}
+
+/// Repair references: create layers entities use but the table lacks, and
+/// remove inserts of blocks that are not defined. Returns the number of
+/// fixes.
+pub fn audit(doc: &mut Document) -> usize {
+    let missing: HashSet<String> = all_entities(doc)
+        .filter(|entity| !doc.layers.contains_key(&entity.layer))
+        .map(|entity| entity.layer.clone())
+        .collect();
+    let mut fixes = missing.len();
+    for name in missing {
+        let mut layer = doc.layers["0"].clone();
+        layer.name = name.clone();
+        doc.layers.insert(name, layer);
+    }
+
+    let defined: HashSet<String> = doc.blocks.keys().cloned().collect();
+    let resolves = |entity: &Entity| match &entity.geometry {
+        GeometryType::Insert(insert) => defined.contains(&insert.block_name),
+        _ => true,
+    };
+    let before = all_entities(doc).count();
+    doc.entities.retain(resolves);
+    for block in doc.blocks.values_mut() {
+        block.entities.retain(resolves);
+    }
+    fixes += before - all_entities(doc).count();
+
+    if fixes > 0 {
+        doc.metadata.modified = chrono::Utc::now();
+    }
+    fixes
+}
+
+/// Drop spline control points that lie within `tolerance` of the control
+/// polygon through their neighbours, for splines with more than
+/// `max_control_points`. Knots are rebuilt as clamped uniform. Returns the
+/// number of splines changed.
+pub fn simplify_splines(doc: &mut Document, max_control_points: usize, tolerance: f64) -> usize {
+    let mut changed = 0;
+    let entities = doc.entities.iter_mut().chain(
+        doc.blocks
+            .values_mut()
+            .flat_map(|block| block.entities.iter_mut()),
+    );
+
+    for entity in entities {
+        if let GeometryType::Spline(spline) = &mut entity.geometry {
+            if spline.control_points.len() > max_control_points
+                && simplify_spline(spline, tolerance)
+            {
+                changed += 1;
+            }
+        }
+    }
+
+    if changed > 0 {
+        doc.metadata.modified = chrono::Utc::now();
+        doc.rebuild_spatial_index();
+    }
+    changed
+}
+
+fn simplify_spline(spline: &mut Spline, tolerance: f64) -> bool {
+    let points = &spline.control_points;
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    douglas_peucker(points, 0, points.len() - 1, tolerance, &mut keep);
+
+    let kept: Vec<usize> = (0..points.len()).filter(|&i| keep[i]).collect();
+    if kept.len() == points.len() {
+        return false;
+    }
+
+    spline.control_points = kept.iter().map(|&i| points[i]).collect();
+    if let Some(weights) = &spline.weights {
+        spline.weights = Some(kept.iter().map(|&i| weights[i]).collect());
+    }
+    spline.degree = spline.degree.min(kept.len() - 1).max(1);
+    spline.knots = clamped_uniform_knots(kept.len(), spline.degree);
+    true
+}
+
+fn douglas_peucker(points: &[Vec3], first: usize, last: usize, tolerance: f64, keep: &mut [bool]) {
+    if last <= first + 1 {
+        return;
+    }
+    let (index, distance) = (first + 1..last)
+        .map(|i| {
+            (
+                i,
+                distance_to_segment(points[i], points[first], points[last]),
+            )
+        })
+        .fold((first, 0.0), |best, candidate| {
+            if candidate.1 > best.1 {
+                candidate
+            } else {
+                best
+            }
+        });
+    if distance > tolerance {
+        keep[index] = true;
+        douglas_peucker(points, first, index, tolerance, keep);
+        douglas_peucker(points, index, last, tolerance, keep);
+    }
+}
+
+fn clamped_uniform_knots(count: usize, degree: usize) -> Vec<f64> {
+    let spans = count - degree;
+    (0..count + degree + 1)
+        .map(|i| (i.saturating_sub(degree)).min(spans) as f64 / spans as f64)
+        .collect()
+}
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    Vec3::new(a.x - b.x, a.y - b.y, a.z - b.z)
+}
+
+fn length(v: Vec3) -> f64 {
+    (v.x * v.x + v.y * v.y + v.z * v.z).sqrt()
+}
+
+fn distance_to_segment(p: Vec3, a: Vec3, b: Vec3) -> f64 {
+    let ab = sub(b, a);
+    let ap = sub(p, a);
+    let len_sq = ab.x * ab.x + ab.y * ab.y + ab.z * ab.z;
+    if len_sq == 0.0 {
+        return length(ap);
+    }
+    let t = ((ap.x * ab.x + ap.y * ab.y + ap.z * ab.z) / len_sq).clamp(0.0, 1.0);
+    length(sub(ap, Vec3::new(ab.x * t, ab.y * t, ab.z * t)))
+}
+
+/// Model space and block entities
+fn all_entities(doc: &Document) -> impl Iterator<Item = &Entity> {
+    doc.entities
+        .iter()
+        .chain(doc.blocks.values().flat_map(|block| block.entities.iter()))
+}
+
+fn unused_layers(doc: &Document) -> Vec<String> {
+    let used: HashSet<&str> = all_entities(doc)
+        .map(|entity| entity.layer.as_str())
+        .collect();
+    let mut unused: Vec<String> = doc
+        .layers
+        .keys()
+        .filter(|name| !used.contains(name.as_str()))
+        .filter(|name| !RESERVED_LAYERS.iter().any(|r| r.eq_ignore_ascii_case(name)))
+        .cloned()
+        .collect();
+    unused.sort();
+    unused
+}
+
+/// Blocks unreachable from model space; anonymous (`*`) blocks are left to
+/// whatever owns them
+fn unused_blocks(doc: &Document) -> Vec<String> {
+    let mut reachable = HashSet::new();
+    let mut pending: Vec<&str> = doc.entities.iter().filter_map(insert_name).collect();
+    while let Some(name) = pending.pop() {
+        if reachable.insert(name) {
+            if let Some(block) = doc.blocks.get(name) {
+                pending.extend(block.entities.iter().filter_map(insert_name));
+            }
+        }
+    }
+
+    let mut unused: Vec<String> = doc
+        .blocks
+        .keys()
+        .filter(|name| !name.starts_with('*') && !reachable.contains(name.as_str()))
+        .cloned()
+        .collect();
+    unused.sort();
+    unused
+}
+
+fn insert_name(entity: &Entity) -> Option<&str> {
+    match &entity.geometry {
+        GeometryType::Insert(insert) => Some(insert.block_name.as_str()),
+        _ => None,
+    }
+}
+
+/// Memoized flattened block costs
+struct BlockCosts<'a> {
+    doc: &'a Document,
+    tolerance: f64,
+    cache: HashMap<String, (usize, usize)>,
+    visiting: HashSet<String>,
+}
+
+impl<'a> BlockCosts<'a> {
+    fn new(doc: &'a Document, tolerance: f64) -> Self {
+        Self {
+            doc,
+            tolerance,
+            cache: HashMap::new(),
+            visiting: HashSet::new(),
+        }
+    }
+
+    /// (entities, segments) for one insert of a block; a block that inserts
+    /// itself counts as empty on the second visit
+    fn block(&mut self, name: &str) -> (usize, usize) {
+        if let Some(&cost) = self.cache.get(name) {
+            return cost;
+        }
+        let Some(block) = self.doc.blocks.get(name) else {
+            return (0, 0);
+        };
+        if !self.visiting.insert(name.to_string()) {
+            return (0, 0);
+        }
+
+        let mut cost = (0, 0);
+        for entity in &block.entities {
+            match &entity.geometry {
+                GeometryType::Insert(insert) => {
+                    let (entities, segments) = self.block(&insert.block_name);
+                    cost.0 += entities;
+                    cost.1 += segments;
+                }
+                geometry => {
+                    cost.0 += 1;
+                    cost.1 += self.segments(geometry);
+                }
+            }
+        }
+
+        self.visiting.remove(name);
+        self.cache.insert(name.to_string(), cost);
+        cost
+    }
+
+    fn segments(&mut self, geometry: &GeometryType) -> usize {
+        let tol = self.tolerance;
+        match geometry {
+            GeometryType::Point(_) | GeometryType::Line(_) => 1,
+            GeometryType::Circle(c) => arc_segments(c.radius, 2.0 * PI, tol),
+            GeometryType::Arc(a) => {
+                let mut sweep = (a.end_angle - a.start_angle) % (2.0 * PI);
+                if sweep <= 0.0 {
+                    sweep += 2.0 * PI;
+                }
+                arc_segments(a.radius, sweep, tol)
+            }
+            GeometryType::Ellipse(e) => arc_segments(e.major_axis, 2.0 * PI, tol),
+            GeometryType::Polyline(p) => polyline_segments(p, tol),
+            GeometryType::Spline(s) => {
+                s.control_points.len().saturating_sub(s.degree).max(1) * SPLINE_SPAN_SEGMENTS
+            }
+            GeometryType::Text(t) => t.text.chars().count() * GLYPH_SEGMENTS,
+            GeometryType::MText(t) => t.text.chars().count() * GLYPH_SEGMENTS,
+            GeometryType::Dimension(_) => DIMENSION_SEGMENTS,
+            GeometryType::Insert(i) => self.block(&i.block_name).1,
+            GeometryType::Hatch(h) => hatch_segments(h),
+            GeometryType::PointCloud(c) => c.len(),
+        }
+    }
+}
+
+/// Chords needed to keep the sagitta under `tolerance`
+fn arc_segments(radius: f64, sweep: f64, tolerance: f64) -> usize {
+    if !radius.is_finite() || tolerance <= 0.0 || radius <= tolerance {
+        return 1;
+    }
+    let step = 2.0 * (1.0 - tolerance / radius).acos();
+    ((sweep / step).ceil() as usize).clamp(1, MAX_CURVE_SEGMENTS)
+}
+
+fn polyline_segments(polyline: &Polyline, tolerance: f64) -> usize {
+    let vertices = &polyline.vertices;
+    let count = if polyline.closed {
+        vertices.len()
+    } else {
+        vertices.len().saturating_sub(1)
+    };
+
+    (0..count)
+        .map(|i| {
+            let v = &vertices[i];
+            let next = &vertices[(i + 1) % vertices.len()];
+            if v.bulge == 0.0 {
+                return 1;
+            }
+            let angle = 4.0 * v.bulge.abs().atan();
+            let chord = length(sub(next.position, v.position));
+            arc_segments(chord / (2.0 * (angle / 2.0).sin()), angle, tolerance)
+        })
+        .sum()
+}
+
+fn hatch_segments(hatch: &Hatch) -> usize {
+    let edges: usize = hatch.boundaries.iter().map(|loop_| loop_.len()).sum();
+    if hatch.pattern.eq_ignore_ascii_case("SOLID") {
+        return edges;
+    }
+
+    let bounds = BoundingBox::from_points(&hatch.boundaries.concat());
+    let size = bounds.size();
+    let diagonal = (size.x * size.x + size.y * size.y).sqrt();
+    let spacing = HATCH_SPACING * hatch.scale.abs().max(1e-6);
+    let lines = if diagonal.is_finite() {
+        (diagonal / spacing).ceil() as usize
+    } else {
+        0
+    };
+    edges + lines
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= MB {
+        format!("{:.1} MB", bytes as f64 / MB)
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(name: &str) -> Layer {
+        let mut layer = Document::new().layers["0"].clone();
+        layer.name = name.to_string();
+        layer
+    }
+
+    fn line(layer: &str) -> Entity {
+        Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::zero(),
+                end: Vec3::unit_x(),
+            }),
+            layer.to_string(),
+        )
+    }
+
+    fn insert(block: &str) -> Entity {
+        Entity::new(
+            GeometryType::Insert(Insert {
+                block_name: block.to_string(),
+                position: Vec3::zero(),
+                scale: Vec3::new(1.0, 1.0, 1.0),
+                rotation: 0.0,
+                attributes: HashMap::new(),
+            }),
+            "0".to_string(),
+        )
+    }
+
+    fn block(name: &str, entities: Vec<Entity>) -> Block {
+        Block {
+            name: name.to_string(),
+            base_point: Vec3::zero(),
+            entities,
+            description: String::new(),
+        }
+    }
+
+    fn sample() -> Document {
+        let mut doc = Document::new();
+        doc.add_layer(layer("WALLS"));
+        doc.add_layer(layer("OLD"));
+        doc.add_layer(layer("NESTED"));
+        doc.add_layer(layer("TEMP"));
+        doc.add_block(block("CHAIR", vec![line("WALLS"), line("WALLS")]));
+        doc.add_block(block("DESK", vec![insert("CHAIR"), line("NESTED")]));
+        doc.add_block(block("SPARE", vec![line("OLD")]));
+        doc.add_entity(line("WALLS"));
+        doc.add_entity(insert("DESK"));
+        doc.add_entity(insert("DESK"));
+        doc.add_entity(insert("MISSING"));
+        doc.add_entity(Entity::new(
+            GeometryType::Circle(Circle {
+                center: Vec3::zero(),
+                radius: 100.0,
+                normal: Vec3::unit_z(),
+            }),
+            "GHOST".to_string(),
+        ));
+        doc.variables
+            .insert(PROXY_COUNT_VARIABLE.to_string(), "3".to_string());
+        doc
+    }
+
+    #[test]
+    fn test_profile() {
+        let report = HealthProfiler::new().profile(&sample());
+
+        assert_eq!(report.entity_count, 5);
+        assert_eq!(report.by_type["Insert"], 3);
+        assert_eq!(report.by_layer["WALLS"], 1);
+
+        assert_eq!(report.heaviest_blocks[0].name, "DESK");
+        assert_eq!(report.heaviest_blocks[0].entities, 3);
+        assert_eq!(report.heaviest_blocks[0].total_segments(), 6);
+
+        // A 100-unit circle at 0.01 tolerance needs hundreds of chords
+        assert_eq!(report.hotspots[0].type_name, "Circle");
+        assert!(report.hotspots[0].segments > 200);
+
+        // OLD is only used inside SPARE, so it is not unused until a purge
+        assert_eq!(report.unused_layers, vec!["TEMP".to_string()]);
+        assert_eq!(report.unused_blocks, vec!["SPARE".to_string()]);
+        assert_eq!(report.unresolved_inserts, 1);
+        assert_eq!(report.undefined_layer_entities, 1);
+        assert_eq!(report.proxy_count, 3);
+        assert!(report.file_size > 0);
+
+        assert!(!report.is_healthy());
+        assert_eq!(
+            report.remediations(),
+            vec![Remediation::Purge, Remediation::Audit]
+        );
+    }
+
+    #[test]
+    fn test_undo_and_size_thresholds() {
+        let doc = Document::new();
+        let report = HealthProfiler::new()
+            .with_file_size(60 * 1024 * 1024)
+            .with_undo_history(90, 100, 12)
+            .profile(&doc);
+
+        assert_eq!(report.issues.len(), 2);
+        assert!(report.issues[0].message.contains("60.0 MB"));
+        assert!(report.issues[1].message.contains("12 levels"));
+        assert!(report.remediations().is_empty());
+    }
+
+    #[test]
+    fn test_purge_and_audit() {
+        let mut doc = sample();
+        let thresholds = HealthThresholds::default();
+
+        assert_eq!(Remediation::Purge.apply(&mut doc, &thresholds), 3);
+        assert!(!doc.blocks.contains_key("SPARE"));
+        assert!(!doc.layers.contains_key("OLD"));
+        assert!(doc.layers.contains_key("0"));
+
+        assert_eq!(Remediation::Audit.apply(&mut doc, &thresholds), 2);
+        assert!(doc.layers.contains_key("GHOST"));
+        assert_eq!(doc.entities.len(), 4);
+
+        let report = HealthProfiler::new().profile(&doc);
+        assert!(report.remediations().is_empty());
+    }
+
+    #[test]
+    fn test_simplify_splines() {
+        let mut control_points: Vec<Vec3> =
+            (0..100).map(|i| Vec3::new(i as f64, 0.0, 0.0)).collect();
+        control_points[50].y = 5.0;
+        let spline = Spline {
+            degree: 3,
+            knots: clamped_uniform_knots(control_points.len(), 3),
+            control_points,
+            weights: None,
+            closed: false,
+        };
+        let mut doc = Document::new();
+        doc.add_entity(Entity::new(GeometryType::Spline(spline), "0".to_string()));
+
+        let report = HealthProfiler::new().profile(&doc);
+        assert_eq!(report.complex_splines, 1);
+        assert_eq!(report.remediations(), vec![Remediation::SimplifySplines]);
+
+        assert_eq!(
+            Remediation::SimplifySplines.apply(&mut doc, &HealthThresholds::default()),
+            1
+        );
+        match &doc.entities[0].geometry {
+            GeometryType::Spline(s) => {
+                assert_eq!(s.control_points.len(), 5);
+                assert_eq!(s.knots.len(), 9);
+                assert_eq!(s.knots[0], 0.0);
+                assert_eq!(s.knots[8], 1.0);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
@@ -31,6 +31,8 @@
 //! - 126: Rational B-Spline Curve (NURBS)
 //! - 128: Rational B-Spline Surface (NURBS)
 
+use crate::geometry::conic::{Conic2D, ConicArc};
+use crate::geometry::point::Point2D;
 use crate::io::document::*;
 use std::collections::HashMap;
 use std::fs::File;
@@ -179,13 +181,13 @@ impl IgesReader {
     /// Read an IGES file
     pub fn read_file<P: AsRef<Path>>(&self, path: P) -> IgesResult<Document> {
         let file = File::open(path)?;
-        let _reader = BufReader::new(file);
+        let reader = BufReader::new(file);
         self.read(reader)
     }
 
     /// Read IGES from a buffered reader
     pub fn read<R: BufRead>(&self, reader: R) -> IgesResult<Document> {
-        let _iges_file = self.parse_iges_file(reader)?;
+        let iges_file = self.parse_iges_file(reader)?;
         self.convert_to_document(iges_file)
     }
 
@@ -249,7 +251,7 @@ impl IgesReader {
     fn parse_directory_section(&self, lines: &[String]) -> IgesResult<Vec<DirectoryEntry>> {
         let mut entries = Vec::new();
 
-        for chunk in lines.chunks(2) {
+        for (index, chunk) in lines.chunks(2).enumerate() {
             if chunk.len() < 2 {
                 break;
             }
@@ -257,7 +259,10 @@ impl IgesReader {
             let line1 = &chunk[0];
             let line2 = &chunk[1];
 
-            entries.push(self.parse_directory_entry(line1, line2)?);
+            let mut entry = self.parse_directory_entry(line1, line2)?;
+            // Parameter lines point back at the entry's first line
+            entry.sequence_number = 2 * index + 1;
+            entries.push(entry);
         }
 
         Ok(entries)
@@ -357,6 +362,7 @@ impl IgesReader {
         match entry.entity_type {
             110 => self.convert_line(entry, iges_file, doc)?,
             100 => self.convert_circular_arc(entry, iges_file, doc)?,
+            104 => self.convert_conic_arc(entry, iges_file, doc)?,
             106 => self.convert_copious_data(entry, iges_file, doc)?,
             126 => self.convert_nurbs_curve(entry, iges_file, doc)?,
             _ => {
@@ -388,6 +394,74 @@ impl IgesReader {
         Ok(())
     }
 
+    /// Conic arcs become exact rational NURBS, so ellipses, parabolas and
+    /// hyperbolas keep their shape for dimensioning and snapping
+    fn convert_conic_arc(
+        &self,
+        entry: &DirectoryEntry,
+        iges_file: &IgesFile,
+        doc: &mut Document,
+    ) -> IgesResult<()> {
+        // 104, A, B, C, D, E, F, ZT, X1, Y1, X2, Y2
+        let params = self.entity_parameters(entry, iges_file)?;
+        if params.len() < 12 {
+            return Err(IgesError::Parse {
+                section: "Parameter".to_string(),
+                line: entry.parameter_data_pointer,
+                message: format!("Conic arc needs 11 parameters, found {}", params.len().saturating_sub(1)),
+            });
+        }
+
+        let conic = Conic2D::new(params[1], params[2], params[3], params[4], params[5], params[6]);
+        let z = params[7];
+        let start = Point2D::new(params[8], params[9]);
+        let end = Point2D::new(params[10], params[11]);
+        let Some(arc) = conic.arc_between(start, end) else {
+            if self.strict_mode {
+                return Err(IgesError::Parse {
+                    section: "Parameter".to_string(),
+                    line: entry.parameter_data_pointer,
+                    message: "Conic arc is degenerate or spans both branches of a hyperbola".to_string(),
+                });
+            }
+            return Ok(());
+        };
+
+        let nurbs = arc.to_nurbs();
+        let spline = Spline {
+            degree: nurbs.degree,
+            control_points: nurbs.control_points.iter().map(|p| Vec3::new(p.x, p.y, z)).collect(),
+            knots: nurbs.knots,
+            weights: Some(nurbs.weights),
+            closed: matches!(arc, ConicArc::Ellipse(_)),
+        };
+        doc.add_entity(Entity::new(GeometryType::Spline(spline), entry.level.to_string()));
+        Ok(())
+    }
+
+    /// An entity's parameter record as numbers, entity type first
+    fn entity_parameters(&self, entry: &DirectoryEntry, iges_file: &IgesFile) -> IgesResult<Vec<f64>> {
+        let lines = iges_file
+            .parameter_data
+            .get(&entry.sequence_number)
+            .ok_or(IgesError::InvalidReference(entry.parameter_data_pointer))?;
+        let global = &iges_file.global_section;
+        let joined = lines.concat();
+        let record = joined.split(global.record_delimiter).next().unwrap_or_default();
+
+        record
+            .split(global.parameter_delimiter)
+            .map(|field| {
+                // Fortran-style double precision exponents: 1.5D2
+                field.trim().replace(['D', 'd'], "E").parse::<f64>().map_err(|e| IgesError::Parse {
+                    section: "Parameter".to_string(),
+                    line: entry.parameter_data_pointer,
+                    message: format!("Invalid number '{}': {}", field.trim(), e),
+                })
+            })
+            .collect()
+    }
+
     fn convert_copious_data(
         &self,
         entry: &DirectoryEntry,
@@ -576,13 +650,13 @@ mod tests {
 
     #[test]
     fn test_iges_reader_creation() {
-        let _reader = IgesReader::new();
+        let reader = IgesReader::new();
         assert!(!reader.strict_mode);
     }
 
     #[test]
     fn test_iges_writer_creation() {
-        let _writer = IgesWriter::new();
+        let writer = IgesWriter::new();
         assert_eq!(writer.units_flag, 2);
     }
 }
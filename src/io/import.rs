@@ -2,7 +2,10 @@
 // File I/O System - Import Formats Module
 // Agent 6 - File I/O System Developer
 
+use crate::geometry::fitting::{self, ArcPolyline};
+use crate::geometry::{BSpline, NurbsCurve, Point2D, Polyline2D};
 use crate::io::document::*;
+use crate::io::vectorize::{self, separate_colors, Bitmap, RasterImage, TraceMode};
 use std::fs::File;
 use std::io::{self, BufRead, BufReader};
 use std::path::Path;
@@ -23,6 +26,154 @@ pub enum ImportError {
 
 pub type ImportResult<T> = Result<T, ImportError>;
 
+/// Curve clean-up run on imported geometry
+///
+/// Each tolerance is in drawing units; `None` skips that step.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CurveFitOptions {
+    /// Replace straight-segment polylines with lines and arcs
+    pub fit_arcs: Option<f64>,
+    /// Refit splines with fewer control points
+    pub simplify_splines: Option<f64>,
+    /// Convert splines to line/arc polylines; takes precedence over
+    /// `simplify_splines`
+    pub splines_to_arcs: Option<f64>,
+}
+
+/// What [`CurveFitOptions::apply`] changed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CurveFitSummary {
+    /// Polylines rewritten with arcs
+    pub polylines_fitted: usize,
+    /// Splines refit with fewer control points
+    pub splines_simplified: usize,
+    /// Splines replaced by line/arc polylines
+    pub splines_converted: usize,
+}
+
+impl CurveFitOptions {
+    /// Whether no step is enabled
+    pub fn is_empty(&self) -> bool {
+        self.fit_arcs.is_none() && self.simplify_splines.is_none() && self.splines_to_arcs.is_none()
+    }
+
+    /// Apply the enabled steps to every entity, including those in blocks
+    pub fn apply(&self, doc: &mut Document) -> CurveFitSummary {
+        let mut summary = CurveFitSummary::default();
+        if self.is_empty() {
+            return summary;
+        }
+
+        let entities = doc.entities.iter_mut().chain(
+            doc.blocks
+                .values_mut()
+                .flat_map(|block| block.entities.iter_mut()),
+        );
+        for entity in entities {
+            match &entity.geometry {
+                GeometryType::Polyline(polyline) => {
+                    if let Some(fitted) = self.fit_arcs.and_then(|t| fit_polyline(polyline, t)) {
+                        entity.geometry = GeometryType::Polyline(fitted);
+                        summary.polylines_fitted += 1;
+                    }
+                }
+                GeometryType::Spline(spline) => {
+                    if let Some(tolerance) = self.splines_to_arcs {
+                        if let Some(polyline) = spline_to_polyline(spline, tolerance) {
+                            entity.geometry = GeometryType::Polyline(polyline);
+                            summary.splines_converted += 1;
+                        }
+                    } else if let Some(tolerance) = self.simplify_splines {
+                        if let Some(simplified) = simplify_spline(spline, tolerance) {
+                            entity.geometry = GeometryType::Spline(simplified);
+                            summary.splines_simplified += 1;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if summary != CurveFitSummary::default() {
+            doc.rebuild_spatial_index();
+        }
+        summary
+    }
+}
+
+/// Refit a polyline of straight segments; `None` if it already has arcs or
+/// fitting saves nothing
+fn fit_polyline(polyline: &Polyline, tolerance: f64) -> Option<Polyline> {
+    if polyline.vertices.len() < 3 || polyline.vertices.iter().any(|v| v.bulge != 0.0) {
+        return None;
+    }
+    let elevation = polyline.vertices[0].position.z;
+    let points: Vec<Point2D> = polyline
+        .vertices
+        .iter()
+        .map(|v| Point2D::new(v.position.x, v.position.y))
+        .collect();
+    let fitted = fitting::fit_arc_polyline(&points, tolerance, polyline.closed);
+    (fitted.vertices.len() < polyline.vertices.len()).then(|| to_polyline(&fitted, elevation))
+}
+
+fn spline_to_polyline(spline: &Spline, tolerance: f64) -> Option<Polyline> {
+    let elevation = spline.control_points.first()?.z;
+    let arcs = match to_curve(spline)? {
+        Ok(bspline) => fitting::spline_to_arcs(&bspline, tolerance),
+        Err(nurbs) => fitting::nurbs_to_arcs(&nurbs, tolerance),
+    };
+    (arcs.vertices.len() >= 2).then(|| to_polyline(&arcs, elevation))
+}
+
+fn simplify_spline(spline: &Spline, tolerance: f64) -> Option<Spline> {
+    let elevation = spline.control_points.first()?.z;
+    let reduced = match to_curve(spline)? {
+        Ok(bspline) => fitting::reduce_control_points(&bspline, tolerance),
+        Err(nurbs) => fitting::reduce_nurbs_control_points(&nurbs, tolerance),
+    }?;
+    Some(Spline {
+        degree: reduced.degree,
+        control_points: reduced
+            .control_points
+            .iter()
+            .map(|p| Vec3::new(p.x, p.y, elevation))
+            .collect(),
+        knots: reduced.knots,
+        weights: None,
+        closed: spline.closed,
+    })
+}
+
+/// Planar curve for a spline; rational splines come back as `Err`
+pub(crate) fn to_curve(spline: &Spline) -> Option<Result<BSpline, NurbsCurve>> {
+    let points: Vec<Point2D> = spline
+        .control_points
+        .iter()
+        .map(|p| Point2D::new(p.x, p.y))
+        .collect();
+    match &spline.weights {
+        Some(weights) if weights.iter().any(|w| (w - weights[0]).abs() > f64::EPSILON) => {
+            NurbsCurve::new(points, weights.clone(), spline.knots.clone(), spline.degree).map(Err)
+        }
+        _ => BSpline::new(points, spline.knots.clone(), spline.degree).map(Ok),
+    }
+}
+
+pub(crate) fn to_polyline(arcs: &ArcPolyline, elevation: f64) -> Polyline {
+    Polyline {
+        vertices: arcs
+            .vertices
+            .iter()
+            .map(|v| Vertex {
+                position: Vec3::new(v.point.x, v.point.y, elevation),
+                bulge: v.bulge,
+            })
+            .collect(),
+        closed: arcs.closed,
+    }
+}
+
 /// SVG import settings
 #[derive(Debug, Clone)]
 pub struct SvgImportSettings {
@@ -34,6 +185,8 @@ pub struct SvgImportSettings {
     pub tolerance: f64,
     /// Convert text to paths
     pub convert_text_to_paths: bool,
+    /// Curve clean-up applied after import
+    pub curve_fitting: CurveFitOptions,
 }
 
 impl Default for SvgImportSettings {
@@ -43,6 +196,7 @@ impl Default for SvgImportSettings {
             default_layer: "0".to_string(),
             tolerance: 0.01,
             convert_text_to_paths: false,
+            curve_fitting: CurveFitOptions::default(),
         }
     }
 }
@@ -61,7 +215,7 @@ impl SvgImporter {
     /// Import an SVG file
     pub fn import<P: AsRef<Path>>(&self, path: P) -> ImportResult<Document> {
         let file = File::open(path)?;
-        let _reader = BufReader::new(file);
+        let reader = BufReader::new(file);
         self.import_from_reader(reader)
     }
 
@@ -94,6 +248,8 @@ impl SvgImporter {
         self.parse_polylines(svg, &mut doc)?;
         self.parse_paths(svg, &mut doc)?;
 
+        self.settings.curve_fitting.apply(&mut doc);
+
         Ok(doc)
     }
 
@@ -456,18 +612,28 @@ impl Default for SvgImporter {
 }
 
 /// Image import settings
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ImageImportSettings {
     /// Trace as vectors
     pub vectorize: bool,
-    /// Tracing threshold
+    /// Tracing threshold; darker pixels are ink
     pub threshold: u8,
-    /// Simplification tolerance
+    /// Ink specks and paper pinholes smaller than this many pixels are
+    /// cleaned up before tracing
+    pub despeckle: usize,
+    /// Trace region outlines or stroke centerlines
+    pub mode: TraceMode,
+    /// Number of ink colors to separate onto their own layers; `0` traces
+    /// a single thresholded layer
+    pub color_layers: usize,
+    /// Simplification tolerance in drawing units
     pub tolerance: f64,
     /// Default layer
     pub default_layer: String,
     /// Scale factor (pixels to units)
     pub scale: f64,
+    /// Curve clean-up applied after tracing
+    pub curve_fitting: CurveFitOptions,
 }
 
 impl Default for ImageImportSettings {
@@ -475,13 +641,26 @@ impl Default for ImageImportSettings {
         Self {
             vectorize: false,
             threshold: 128,
+            despeckle: 4,
+            mode: TraceMode::Outline,
+            color_layers: 0,
             tolerance: 1.0,
             default_layer: "0".to_string(),
             scale: 1.0,
+            curve_fitting: CurveFitOptions::default(),
         }
     }
 }
 
+/// Thresholded and despeckled image, for previewing before tracing
+#[derive(Debug, Clone, PartialEq)]
+pub struct TracePreview {
+    /// Ink left after clean-up
+    pub bitmap: Bitmap,
+    /// Specks and pinholes removed by despeckling
+    pub specks_removed: usize,
+}
+
 /// Image importer (for bitmap to vector conversion)
 pub struct ImageImporter {
     settings: ImageImportSettings,
@@ -494,14 +673,124 @@ impl ImageImporter {
     }
 
     /// Import an image file
-    pub fn import<P: AsRef<Path>>(&self, _path: P) -> ImportResult<Document> {
-        // This would require image processing and vectorization
-        // Using the image crate and a vectorization algorithm (e.g., Potrace)
+    pub fn import<P: AsRef<Path>>(&self, path: P) -> ImportResult<Document> {
+        if !self.settings.vectorize {
+            return self.import_as_reference(path);
+        }
+        let image = Self::load(path)?;
+        Ok(self.vectorize(&image))
+    }
+
+    /// Decode an image file
+    #[cfg(feature = "native")]
+    pub fn load<P: AsRef<Path>>(path: P) -> ImportResult<RasterImage> {
+        let decoded = image::open(path)
+            .map_err(|e| ImportError::InvalidFormat(e.to_string()))?
+            .to_rgb8();
+        let (width, height) = decoded.dimensions();
+        Ok(RasterImage::from_rgb(width, height, decoded.as_raw())
+            .expect("decoded buffer matches its dimensions"))
+    }
+
+    /// Decode an image file
+    #[cfg(not(feature = "native"))]
+    pub fn load<P: AsRef<Path>>(_path: P) -> ImportResult<RasterImage> {
         Err(ImportError::UnsupportedFormat(
-            "Image vectorization not yet implemented".to_string(),
+            "Image decoding requires the native feature".to_string(),
         ))
     }
 
+    /// Threshold and despeckle with the current settings
+    ///
+    /// Cheap enough to rerun as the threshold or despeckle size is
+    /// adjusted. With color separation enabled, every ink layer is shown
+    /// together.
+    pub fn preview(&self, image: &RasterImage) -> TracePreview {
+        let mut bitmap = if self.settings.color_layers > 0 {
+            let mut merged = Bitmap::new(image.width, image.height);
+            for layer in separate_colors(image, self.settings.color_layers) {
+                for y in 0..image.height {
+                    for x in 0..image.width {
+                        if layer.bitmap.get(x as i64, y as i64) {
+                            merged.set(x, y, true);
+                        }
+                    }
+                }
+            }
+            merged
+        } else {
+            Bitmap::threshold(image, self.settings.threshold)
+        };
+        let specks_removed = bitmap.despeckle(self.settings.despeckle);
+        TracePreview { bitmap, specks_removed }
+    }
+
+    /// Trace an image into a document
+    ///
+    /// Each separated color goes on a layer named `IMAGE-RRGGBB` in that
+    /// color; otherwise everything lands on the default layer.
+    pub fn vectorize(&self, image: &RasterImage) -> Document {
+        let mut doc = Document::new();
+
+        if self.settings.color_layers == 0 {
+            let mut bitmap = Bitmap::threshold(image, self.settings.threshold);
+            bitmap.despeckle(self.settings.despeckle);
+            self.trace(&bitmap, &self.settings.default_layer, &mut doc);
+        } else {
+            for mut layer in separate_colors(image, self.settings.color_layers) {
+                let [r, g, b] = layer.color;
+                let name = format!("IMAGE-{:02X}{:02X}{:02X}", r, g, b);
+                doc.add_layer(Layer {
+                    name: name.clone(),
+                    color: Color::new(r, g, b),
+                    line_type: LineType::Continuous,
+                    line_weight: LineWeight::Default,
+                    visible: true,
+                    locked: false,
+                    frozen: false,
+                    plottable: true,
+                    confidential: false,
+                });
+                layer.bitmap.despeckle(self.settings.despeckle);
+                self.trace(&layer.bitmap, &name, &mut doc);
+            }
+        }
+
+        self.settings.curve_fitting.apply(&mut doc);
+        doc
+    }
+
+    /// Trace one bitmap onto `layer`, flipping to y-up drawing coordinates
+    fn trace(&self, bitmap: &Bitmap, layer: &str, doc: &mut Document) {
+        let paths = match self.settings.mode {
+            TraceMode::Outline => vectorize::trace_outlines(bitmap),
+            TraceMode::Centerline => vectorize::trace_centerlines(&bitmap.skeletonize()),
+        };
+        let scale = self.settings.scale;
+        let height = bitmap.height() as f64;
+
+        for path in paths {
+            let points = path
+                .points
+                .iter()
+                .map(|p| Point2D::new(p.x * scale, (height - p.y) * scale))
+                .collect();
+            let simplified = Polyline2D::new(points, path.closed).simplify(self.settings.tolerance);
+            if simplified.vertices.len() < 2 {
+                continue;
+            }
+            let vertices = simplified
+                .vertices
+                .iter()
+                .map(|p| Vertex { position: Vec3::new(p.x, p.y, 0.0), bulge: 0.0 })
+                .collect();
+            doc.add_entity(Entity::new(
+                GeometryType::Polyline(Polyline { vertices, closed: path.closed }),
+                layer.to_string(),
+            ));
+        }
+    }
+
     /// Import as raster reference (placeholder)
     pub fn import_as_reference<P: AsRef<Path>>(&self, _path: P) -> ImportResult<Document> {
         // This would import the image as a reference entity in the document
@@ -618,7 +907,7 @@ mod tests {
     fn test_svg_import_line() {
         let svg = r#"<svg><line x1="0" y1="0" x2="100" y2="100" /></svg>"#;
         let importer = SvgImporter::default();
-        let _doc = importer.import_from_string(svg).unwrap();
+        let doc = importer.import_from_string(svg).unwrap();
 
         assert_eq!(doc.entities.len(), 1);
         match &doc.entities[0].geometry {
@@ -631,7 +920,7 @@ mod tests {
     fn test_svg_import_circle() {
         let svg = r#"<svg><circle cx="50" cy="50" r="25" /></svg>"#;
         let importer = SvgImporter::default();
-        let _doc = importer.import_from_string(svg).unwrap();
+        let doc = importer.import_from_string(svg).unwrap();
 
         assert_eq!(doc.entities.len(), 1);
         match &doc.entities[0].geometry {
@@ -647,7 +936,7 @@ mod tests {
     fn test_svg_import_polygon() {
         let svg = r#"<svg><polygon points="0,0 100,0 100,100 0,100" /></svg>"#;
         let importer = SvgImporter::default();
-        let _doc = importer.import_from_string(svg).unwrap();
+        let doc = importer.import_from_string(svg).unwrap();
 
         assert_eq!(doc.entities.len(), 1);
         match &doc.entities[0].geometry {
@@ -658,4 +947,139 @@ mod tests {
             _ => panic!("Expected polyline"),
         }
     }
+
+    #[test]
+    fn test_curve_fitting_post_processing() {
+        // Circle drawn as a 72-gon, plus a wiggly cubic spline
+        let vertices = (0..72)
+            .map(|i| {
+                let angle = i as f64 * std::f64::consts::PI / 36.0;
+                Vertex {
+                    position: Vec3::new(10.0 * angle.cos(), 10.0 * angle.sin(), 2.0),
+                    bulge: 0.0,
+                }
+            })
+            .collect();
+        let control_points: Vec<Vec3> = (0..30)
+            .map(|i| Vec3::new(i as f64, (i as f64 * 0.3).sin(), 0.0))
+            .collect();
+        let spline = BSpline::clamped(
+            control_points.iter().map(|p| Point2D::new(p.x, p.y)).collect(),
+            3,
+        )
+        .unwrap();
+
+        let mut doc = Document::new();
+        doc.add_entity(Entity::new(
+            GeometryType::Polyline(Polyline { vertices, closed: true }),
+            "0".to_string(),
+        ));
+        doc.add_entity(Entity::new(
+            GeometryType::Spline(Spline {
+                degree: 3,
+                control_points,
+                knots: spline.knots,
+                weights: None,
+                closed: false,
+            }),
+            "0".to_string(),
+        ));
+        let mut converted = doc.clone();
+
+        let summary = CurveFitOptions {
+            fit_arcs: Some(0.05),
+            simplify_splines: Some(0.01),
+            splines_to_arcs: None,
+        }
+        .apply(&mut doc);
+        assert_eq!(summary.polylines_fitted, 1);
+        assert_eq!(summary.splines_simplified, 1);
+        match &doc.entities[0].geometry {
+            GeometryType::Polyline(p) => {
+                assert!(p.closed);
+                assert!(p.vertices.len() < 8);
+                assert!(p.vertices.iter().all(|v| v.bulge > 0.0 && v.position.z == 2.0));
+            }
+            _ => panic!("Expected polyline"),
+        }
+        match &doc.entities[1].geometry {
+            GeometryType::Spline(s) => assert!(s.control_points.len() < 30),
+            _ => panic!("Expected spline"),
+        }
+
+        let summary = CurveFitOptions {
+            splines_to_arcs: Some(0.01),
+            ..Default::default()
+        }
+        .apply(&mut converted);
+        assert_eq!(summary.splines_converted, 1);
+        assert_eq!(summary.polylines_fitted, 0);
+        assert!(matches!(&converted.entities[1].geometry, GeometryType::Polyline(_)));
+    }
+
+    #[test]
+    fn test_image_tracing() {
+        // Black "L" stroke 3px wide on a 40x40 scan, plus scanner noise
+        let image = RasterImage::from_fn(40, 40, |x, y| {
+            let stroke = ((9..12).contains(&x) && (5..35).contains(&y))
+                || ((9..35).contains(&x) && (32..35).contains(&y));
+            if stroke || (x, y) == (30, 8) {
+                [10, 10, 10]
+            } else {
+                [240, 240, 240]
+            }
+        });
+        let mut settings = ImageImportSettings {
+            vectorize: true,
+            tolerance: 0.5,
+            scale: 0.5,
+            ..ImageImportSettings::default()
+        };
+
+        let preview = ImageImporter::new(settings.clone()).preview(&image);
+        assert_eq!(preview.specks_removed, 1);
+        assert_eq!(preview.bitmap.ink_pixels(), 3 * 30 + 3 * 23);
+
+        // Outline: one closed boundary
+        let doc = ImageImporter::new(settings.clone()).vectorize(&image);
+        assert_eq!(doc.entities.len(), 1);
+        match &doc.entities[0].geometry {
+            GeometryType::Polyline(p) => {
+                assert!(p.closed);
+                assert_eq!(p.vertices.len(), 6);
+            }
+            _ => panic!("Expected polyline"),
+        }
+
+        // Centerline: one open stroke with its corner near (10.5, 33.5) px
+        settings.mode = TraceMode::Centerline;
+        let doc = ImageImporter::new(settings.clone()).vectorize(&image);
+        assert_eq!(doc.entities.len(), 1);
+        match &doc.entities[0].geometry {
+            GeometryType::Polyline(p) => {
+                assert!(!p.closed);
+                assert!(p.vertices.len() <= 4);
+                let corner = Point2D::new(10.5 * 0.5, (40.0 - 33.5) * 0.5);
+                assert!(p
+                    .vertices
+                    .iter()
+                    .any(|v| Point2D::new(v.position.x, v.position.y).distance_to(&corner) < 1.0));
+            }
+            _ => panic!("Expected polyline"),
+        }
+
+        // Two ink colors land on their own layers
+        let image = RasterImage::from_fn(40, 20, |x, y| match (x, y) {
+            (5..15, 5..15) => [220, 30, 30],
+            (25..35, 5..15) => [30, 30, 220],
+            _ => [255, 255, 255],
+        });
+        settings.mode = TraceMode::Outline;
+        settings.color_layers = 2;
+        let doc = ImageImporter::new(settings).vectorize(&image);
+        assert_eq!(doc.entities.len(), 2);
+        let layers: Vec<&str> = doc.entities.iter().map(|e| e.layer.as_str()).collect();
+        assert_ne!(layers[0], layers[1]);
+        assert!(layers.iter().all(|l| l.starts_with("IMAGE-") && doc.get_layer(l).is_some()));
+    }
 }
@@ -0,0 +1,259 @@
+// CADDY - Enterprise CAD System
+// File I/O System - Legacy Document Layouts Module
+// Agent 6 - File I/O System Developer
+
+//! Document layouts of earlier native file versions.
+//!
+//! Native `.cdy` files are bincode, which stores neither field names nor
+//! defaults: a payload only reads back into the exact types that wrote it,
+//! and `#[serde(default)]` has no effect. Whenever the document layout
+//! changes, the native format version is bumped and the previous layout is
+//! frozen here, so older files are read into it and migrated on load.
+//!
+//! These types must never change.
+
+use crate::io::document::{
+    Arc, Block, Circle, Color, Dimension, Document, DocumentMetadata, DocumentSettings, Ellipse,
+    Entity, GeometryType, GridSettings, Hatch, Insert, Layer, Line, LineType, LineWeight, MText,
+    PaperSize, Point, Polyline, SnapSettings, Spline, Text, Vec3, View,
+};
+use crate::io::native::FileMetadata;
+use crate::io::units::{PrecisionSettings, Unit};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// File container of native files up to version 2
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ContainerV2 {
+    pub(crate) version: u32,
+    pub(crate) document: DocumentV2,
+    pub(crate) metadata: FileMetadata,
+}
+
+/// Document as stored by native files up to version 2
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct DocumentV2 {
+    pub(crate) id: Uuid,
+    pub(crate) metadata: DocumentMetadata,
+    pub(crate) settings: SettingsV2,
+    pub(crate) entities: Vec<EntityV2>,
+    pub(crate) layers: HashMap<String, LayerV2>,
+    pub(crate) blocks: HashMap<String, BlockV2>,
+    pub(crate) views: HashMap<String, View>,
+    pub(crate) variables: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SettingsV2 {
+    pub(crate) units: Unit,
+    pub(crate) precision: PrecisionSettings,
+    pub(crate) paper_size: PaperSize,
+    pub(crate) background_color: Color,
+    pub(crate) grid: GridV2,
+    pub(crate) snap: SnapV2,
+    pub(crate) autosave_interval: Option<u64>,
+    pub(crate) create_backup: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct GridV2 {
+    pub(crate) enabled: bool,
+    pub(crate) major_spacing: f64,
+    pub(crate) minor_spacing: f64,
+    pub(crate) color: Color,
+    pub(crate) snap: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SnapV2 {
+    pub(crate) enabled: bool,
+    pub(crate) endpoint: bool,
+    pub(crate) midpoint: bool,
+    pub(crate) center: bool,
+    pub(crate) intersection: bool,
+    pub(crate) perpendicular: bool,
+    pub(crate) tangent: bool,
+    pub(crate) threshold: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct EntityV2 {
+    pub(crate) id: Uuid,
+    pub(crate) layer: String,
+    pub(crate) color: Option<Color>,
+    pub(crate) line_type: Option<LineType>,
+    pub(crate) line_weight: Option<LineWeight>,
+    pub(crate) visible: bool,
+    pub(crate) geometry: GeometryV2,
+    pub(crate) attributes: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum GeometryV2 {
+    Point(Point),
+    Line(Line),
+    Circle(Circle),
+    Arc(Arc),
+    Ellipse(Ellipse),
+    Polyline(Polyline),
+    Spline(Spline),
+    Text(Text),
+    MText(MText),
+    Dimension(Dimension),
+    Insert(Insert),
+    Hatch(HatchV2),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct HatchV2 {
+    pub(crate) pattern: String,
+    pub(crate) scale: f64,
+    pub(crate) angle: f64,
+    pub(crate) boundaries: Vec<Vec<Vec3>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct LayerV2 {
+    pub(crate) name: String,
+    pub(crate) color: Color,
+    pub(crate) line_type: LineType,
+    pub(crate) line_weight: LineWeight,
+    pub(crate) visible: bool,
+    pub(crate) locked: bool,
+    pub(crate) frozen: bool,
+    pub(crate) plottable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct BlockV2 {
+    pub(crate) name: String,
+    pub(crate) base_point: Vec3,
+    pub(crate) entities: Vec<EntityV2>,
+    pub(crate) description: String,
+}
+
+impl From<DocumentV2> for Document {
+    fn from(old: DocumentV2) -> Self {
+        let mut doc = Document::new();
+        doc.id = old.id;
+        doc.metadata = old.metadata;
+        doc.settings = old.settings.into();
+        doc.entities = old.entities.into_iter().map(Entity::from).collect();
+        doc.layers = old.layers.into_iter().map(|(name, layer)| (name, layer.into())).collect();
+        doc.blocks = old.blocks.into_iter().map(|(name, block)| (name, block.into())).collect();
+        doc.views = old.views;
+        doc.variables = old.variables;
+        doc
+    }
+}
+
+impl From<SettingsV2> for DocumentSettings {
+    fn from(old: SettingsV2) -> Self {
+        Self {
+            units: old.units,
+            precision: old.precision,
+            paper_size: old.paper_size,
+            background_color: old.background_color,
+            grid: old.grid.into(),
+            snap: old.snap.into(),
+            autosave_interval: old.autosave_interval,
+            create_backup: old.create_backup,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<GridV2> for GridSettings {
+    fn from(old: GridV2) -> Self {
+        Self {
+            enabled: old.enabled,
+            major_spacing: old.major_spacing,
+            minor_spacing: old.minor_spacing,
+            color: old.color,
+            snap: old.snap,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<SnapV2> for SnapSettings {
+    fn from(old: SnapV2) -> Self {
+        Self {
+            enabled: old.enabled,
+            endpoint: old.endpoint,
+            midpoint: old.midpoint,
+            center: old.center,
+            intersection: old.intersection,
+            perpendicular: old.perpendicular,
+            tangent: old.tangent,
+            threshold: old.threshold,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<EntityV2> for Entity {
+    fn from(old: EntityV2) -> Self {
+        Self {
+            id: old.id,
+            layer: old.layer,
+            color: old.color,
+            line_type: old.line_type,
+            line_weight: old.line_weight,
+            visible: old.visible,
+            geometry: old.geometry.into(),
+            attributes: old.attributes,
+        }
+    }
+}
+
+impl From<GeometryV2> for GeometryType {
+    fn from(old: GeometryV2) -> Self {
+        match old {
+            GeometryV2::Point(p) => GeometryType::Point(p),
+            GeometryV2::Line(l) => GeometryType::Line(l),
+            GeometryV2::Circle(c) => GeometryType::Circle(c),
+            GeometryV2::Arc(a) => GeometryType::Arc(a),
+            GeometryV2::Ellipse(e) => GeometryType::Ellipse(e),
+            GeometryV2::Polyline(p) => GeometryType::Polyline(p),
+            GeometryV2::Spline(s) => GeometryType::Spline(s),
+            GeometryV2::Text(t) => GeometryType::Text(t),
+            GeometryV2::MText(t) => GeometryType::MText(t),
+            GeometryV2::Dimension(d) => GeometryType::Dimension(d),
+            GeometryV2::Insert(i) => GeometryType::Insert(i),
+            GeometryV2::Hatch(h) => GeometryType::Hatch(Hatch {
+                scale: h.scale,
+                angle: h.angle,
+                ..Hatch::new(h.pattern, h.boundaries)
+            }),
+        }
+    }
+}
+
+impl From<LayerV2> for Layer {
+    fn from(old: LayerV2) -> Self {
+        Self {
+            name: old.name,
+            color: old.color,
+            line_type: old.line_type,
+            line_weight: old.line_weight,
+            visible: old.visible,
+            locked: old.locked,
+            frozen: old.frozen,
+            plottable: old.plottable,
+            confidential: false,
+        }
+    }
+}
+
+impl From<BlockV2> for Block {
+    fn from(old: BlockV2) -> Self {
+        Self {
+            name: old.name,
+            base_point: old.base_point,
+            entities: old.entities.into_iter().map(Entity::from).collect(),
+            description: old.description,
+        }
+    }
+}
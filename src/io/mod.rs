@@ -7,11 +7,52 @@
 //! This module provides comprehensive file input/output functionality for CADDY,
 //! including support for:
 //!
-//! - **Native formats**: Binary (.cdy) and JSON (.cdyj) formats with compression
+//! - **Native formats**: Binary (.cdy) and JSON (.cdyj) formats with compression;
+//!   binary files can be encrypted with an open password
+//! - **Workspace encryption**: cached cloud documents and autosaves sealed
+//!   at rest under a per-user key kept in the OS keychain
 //! - **DXF support**: Full DXF R12 through R2018 compatibility for AutoCAD interoperability
-//! - **Export formats**: SVG, PDF, PNG, JPEG for presentations and sharing
-//! - **Import formats**: SVG and image vectorization
+//! - **Export formats**: SVG, PDF, PNG, JPEG for presentations and sharing;
+//!   PNG and TIFF render in tiles, so output size is not capped by the GPU
+//! - **3D printing**: 3MF packages with units, colors and build plate
+//!   placement, recommended over STL, which keeps neither units nor colors
+//! - **Import formats**: SVG and image vectorization; imported polylines
+//!   can be refit with arcs and splines simplified or converted to arcs
+//! - **Image tracing**: outline or centerline tracing of scanned linework,
+//!   with threshold/despeckle preview and separation of ink colors
 //! - **Unit handling**: Comprehensive unit conversion and formatting
+//! - **Regional presets**: ISO, ANSI, DIN and JIS conventions for units,
+//!   paper sizes, dimension standards and date formats, chosen at first
+//!   run or carried by templates
+//! - **Readouts**: cartesian, polar and station/offset coordinates, with
+//!   fractional or feet-and-inch distances and DMS or bearing angles
+//! - **Transmittals**: ZIP packages of a drawing with its xrefs, images,
+//!   fonts, and plot styles, optionally password protected
+//! - **Clipboard**: cross-document copy, paste and drag that carry layer
+//!   and block definitions along, renaming on conflicts
+//! - **Recycle bin**: soft-deleted entities and documents kept restorable
+//!   for a retention window
+//! - **Export pipelines**: reusable rule files routing layers, blocks and
+//!   classifications to different outputs and formats in one run
+//! - **Redaction**: confidential layers and document metadata withheld
+//!   from DXF and PDF exports, or rasterized in PDF
+//! - **Hatch patterns**: AutoCAD `.pat` pattern libraries, read and written
+//! - **Point clouds**: LAS, XYZ and PTS scans, thinned on import
+//! - **Text fields**: file name, dates, revision, sheet and project fields
+//!   embedded in text and title block attributes, evaluated when displayed
+//!   or plotted
+//! - **Tags**: free-form tags and hierarchical classifications on entities,
+//!   kept in native and DXF files, with saved queries driving selection,
+//!   visibility, export and takeoff
+//! - **Groups and named selections**: nestable groups picked as a unit and
+//!   saved selections, kept in the document and looked up by name along
+//!   with saved queries by scripts and plugins
+//! - **Parameters**: a document table of named values with units and
+//!   expressions, driving dimensional constraints, array counts and fields
+//! - **Progressive loading**: large drawings saved in spatial tiles, with
+//!   the tiles in view loaded first and the rest streamed in the background
+//! - **Drawing health**: profiling of entity counts, heavy blocks,
+//!   tessellation cost and unused definitions, with purge/audit fixes
 //!
 //! ## Quick Start
 //!
@@ -27,7 +68,7 @@
 //! use caddy::io::document::Document;
 //! use caddy::io::native::NativeFormat;
 //!
-//! let _doc = Document::new();
+//! let doc = Document::new();
 //! let format = NativeFormat::new();
 //! format.save(&doc, "drawing.cdy").unwrap();
 //! ```
@@ -37,11 +78,11 @@
 //! use caddy::io::dxf::{DxfReader, DxfWriter, DxfVersion};
 //!
 //! // Import DXF
-//! let _reader = DxfReader::new();
-//! let _doc = reader.read_file("drawing.dxf").unwrap();
+//! let reader = DxfReader::new();
+//! let doc = reader.read_file("drawing.dxf").unwrap();
 //!
 //! // Export DXF
-//! let _writer = DxfWriter::new(DxfVersion::R2018);
+//! let writer = DxfWriter::new(DxfVersion::R2018);
 //! writer.write_file(&doc, "output.dxf").unwrap();
 //! ```
 //!
@@ -50,7 +91,7 @@
 //! use caddy::io::export::{SvgExporter, SvgExportSettings};
 //! use caddy::io::document::Document;
 //!
-//! let _doc = Document::new();
+//! let doc = Document::new();
 //! let settings = SvgExportSettings::default();
 //! let exporter = SvgExporter::new(settings);
 //! exporter.export(&doc, "output.svg").unwrap();
@@ -58,6 +99,8 @@
 
 pub mod document;
 pub mod units;
+pub mod readout;
+pub mod vectorize;
 pub mod dxf;
 pub mod dwg;
 pub mod step;
@@ -65,25 +108,54 @@ pub mod iges;
 pub mod stl;
 pub mod obj;
 pub mod gltf;
+#[cfg(feature = "native")]
+pub mod threemf;
+pub mod health;
 pub mod native;
+mod legacy;
+pub mod progressive;
 pub mod export;
 pub mod import;
+pub mod redaction;
+pub mod pipeline;
+pub mod clipboard;
+pub mod fields;
+pub mod regional;
+pub mod parameters;
+pub mod tags;
+pub mod groups;
+#[cfg(feature = "parallel")]
 pub mod batch;
+#[cfg(feature = "native")]
+pub mod raster;
+#[cfg(feature = "native")]
+pub mod transmittal;
+pub mod trash;
 pub mod validation;
+#[cfg(feature = "native")]
+pub mod vault;
+pub mod pat;
+pub mod pointcloud;
 
 // Re-export commonly used types
 pub use document::{
-    Document, DocumentMetadata, DocumentSettings, Entity, GeometryType,
+    Document, DocumentMetadata, DocumentSettings, Revisions, Entity, GeometryType,
     Layer, Block, View, Color, LineType, LineWeight, Vec3, BoundingBox,
     // Geometry types
     Point, Line, Circle, Arc, Ellipse, Polyline, Spline,
     Text, MText, Dimension, Insert, Hatch,
     // Settings
-    PaperSize, GridSettings, SnapSettings,
+    PaperSize, GridSettings, GridType, Isoplane, SnapSettings,
 };
 
 pub use units::{Unit, UnitConverter, PrecisionSettings};
 
+pub use readout::{
+    Alignment, AngleFormat, CoordinateMode, DistanceFormat, ReadoutFormat, StationFormat,
+};
+
+pub use vectorize::{Bitmap, ColorLayer, RasterImage, TraceMode, TracedPath};
+
 pub use dxf::{DxfReader, DxfWriter, DxfVersion, DxfError, DxfResult};
 
 pub use native::{
@@ -91,6 +163,10 @@ pub use native::{
     BackupManager, NativeError, NativeResult,
 };
 
+pub use progressive::{
+    view_bounds, LoadProgress, ProgressiveLoad, ProgressiveLoader, TiledFormat, DEFAULT_TILE_SIZE,
+};
+
 pub use export::{
     SvgExporter, SvgExportSettings,
     PdfExporter, PdfExportSettings,
@@ -101,8 +177,9 @@ pub use export::{
 
 pub use import::{
     SvgImporter, SvgImportSettings,
-    ImageImporter, ImageImportSettings,
+    ImageImporter, ImageImportSettings, TracePreview,
     Importer, BatchImporter,
+    CurveFitOptions, CurveFitSummary,
     ImportError, ImportResult,
 };
 
@@ -118,11 +195,69 @@ pub use obj::{ObjReader, ObjWriter, ObjMesh, ObjMaterial, ObjError, ObjResult};
 
 pub use gltf::{GltfReader, GltfWriter, Gltf, GltfError, GltfResult};
 
+#[cfg(feature = "native")]
+pub use threemf::{
+    ThreeMfReader, ThreeMfWriter, ThreeMfModel, ThreeMfObject, ThreeMfItem, ThreeMfMaterial,
+    ThreeMfUnit, ThreeMfError, ThreeMfResult,
+};
+
+#[cfg(feature = "parallel")]
 pub use batch::{
     BatchConverter, BatchJob, BatchError, BatchResult, BatchStats,
     ConversionResult, FileFormat,
 };
 
+pub use redaction::{Redaction, RedactionAction, RedactionProfile, RedactionSummary};
+
+pub use pipeline::{
+    ExportPipeline, ExportRule, ExportSelector, OutputStatus, PipelineFormat, PipelineReport,
+    RuleOutcome, PipelineError, PipelineResult,
+};
+
+pub use health::{
+    HealthProfiler, HealthReport, HealthIssue, HealthSeverity, HealthThresholds,
+    Remediation,
+};
+
+pub use clipboard::{
+    ClipboardContent, ConflictPolicy, PasteOptions, PasteReport,
+    ClipboardError, ClipboardResult,
+};
+
+pub use fields::{Field, FieldContext, FieldSource};
+
+pub use regional::{DimensionStandard, Region, RegionalPreset, RegionalSettings};
+
+pub use parameters::{Parameter, ParameterError, ParameterResult, ParameterValues, Parameters};
+
+pub use tags::{EntityQuery, TagError, TagResult};
+
+pub use groups::{Group, NamedSelection, GroupError, GroupResult};
+
+pub use trash::{
+    RecycleBin, TrashSettings, TrashItem, TrashedObject, PurgeRecord,
+    TrashError, TrashResult,
+};
+
+#[cfg(feature = "native")]
+pub use raster::{encode_png, RasterFormat, RasterOutput, MAX_TILE_SIZE};
+
+#[cfg(feature = "native")]
+pub use transmittal::{
+    Transmittal, TransmittalOptions, TransmittalPackage, TransmittalManifest,
+    TransmittalError, TransmittalResult,
+};
+
+#[cfg(feature = "native")]
+pub use vault::{Keychain, MemoryKeychain, VaultError, VaultResult, WorkspaceVault};
+
+pub use pat::{parse_pat, read_pat_file, write_pat, PatError, PatResult};
+
+pub use pointcloud::{
+    import_point_cloud, read_las, read_pts, read_xyz, PointCloudFormat, PointCloudImportOptions,
+    PointCloudError, PointCloudResult,
+};
+
 pub use validation::{
     Validator, Repairer, ValidationError, ValidationIssue, ValidationResult,
     Severity, ValidationReport,
@@ -203,10 +338,15 @@ impl Default for FileFormatInfo {
                     extension: "iges".to_string(),
                     description: "IGES surface geometry (ANSI/US PRO/IPO-100)".to_string(),
                 },
+                FormatEntry {
+                    name: "3MF".to_string(),
+                    extension: "3mf".to_string(),
+                    description: "3D Manufacturing Format with units and colors".to_string(),
+                },
                 FormatEntry {
                     name: "STL".to_string(),
                     extension: "stl".to_string(),
-                    description: "STereoLithography format for 3D printing".to_string(),
+                    description: "STereoLithography triangle meshes without units".to_string(),
                 },
                 FormatEntry {
                     name: "Wavefront OBJ".to_string(),
@@ -255,10 +395,15 @@ impl Default for FileFormatInfo {
                     extension: "iges".to_string(),
                     description: "IGES surface geometry (ANSI/US PRO/IPO-100)".to_string(),
                 },
+                FormatEntry {
+                    name: "3MF".to_string(),
+                    extension: "3mf".to_string(),
+                    description: "3D Manufacturing Format, recommended for 3D printing".to_string(),
+                },
                 FormatEntry {
                     name: "STL Binary".to_string(),
                     extension: "stl".to_string(),
-                    description: "STereoLithography format for 3D printing".to_string(),
+                    description: "STereoLithography for older 3D printing tools".to_string(),
                 },
                 FormatEntry {
                     name: "Wavefront OBJ".to_string(),
@@ -433,7 +578,7 @@ mod tests {
 
     #[test]
     fn test_format_entry() {
-        let _entry = FormatEntry {
+        let entry = FormatEntry {
             name: "Test Format".to_string(),
             extension: "test".to_string(),
             description: "Test description".to_string(),
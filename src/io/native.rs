@@ -2,7 +2,14 @@
 // File I/O System - Native Format Module
 // Agent 6 - File I/O System Developer
 
+#[cfg(feature = "native")]
+use crate::enterprise::crypto::kdf::{Argon2Config, KdfProvider};
+#[cfg(feature = "native")]
+use crate::enterprise::crypto::symmetric::{Aes256GcmCipher, EncryptedData};
+#[cfg(feature = "native")]
+use crate::io::vault::{VaultError, WorkspaceVault};
 use crate::io::document::Document;
+use crate::io::legacy::ContainerV2;
 use std::fs::File;
 use std::io::{self, BufReader, BufWriter, Read, Write};
 use std::path::Path;
@@ -24,18 +31,54 @@ pub enum NativeError {
     InvalidFormat,
     #[error("Compression error: {0}")]
     Compression(String),
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+    #[error("File is password protected; a password is required")]
+    PasswordRequired,
+    #[error("Wrong password or corrupted file")]
+    Decryption,
+    #[cfg(feature = "native")]
+    #[error("Workspace encryption error: {0}")]
+    Vault(#[from] VaultError),
 }
 
 pub type NativeResult<T> = Result<T, NativeError>;
 
 /// CADDY native file format version
-const CURRENT_VERSION: u32 = 1;
-const MAGIC_BYTES: &[u8; 4] = b"CDDY";
+///
+/// Version 2 adds an encryption byte after the compression flag. Version 4
+/// keeps that header and stores the current document layout; bincode ignores
+/// serde defaults, so files up to version 2 are read into the layout frozen
+/// in `io::legacy` and migrated. Any change to the document layout needs a
+/// new version and a frozen copy of the previous layout.
+const CURRENT_VERSION: u32 = 4;
+/// Last version storing the document layout frozen in `io::legacy`
+const LEGACY_VERSION: u32 = 2;
+/// Version 3 stores entities in spatial tiles behind a directory, so the
+/// visible part of a drawing can be read first; see [`crate::io::progressive`]
+pub(crate) const TILED_VERSION: u32 = 3;
+pub(crate) const MAGIC_BYTES: &[u8; 4] = b"CDDY";
+
+/// Encryption byte values
+const ENCRYPTION_NONE: u8 = 0;
+const ENCRYPTION_AES_GCM: u8 = 1;
+/// Argon2id memory/time/parallelism costs (u32 LE each) and salt
+const SALT_SIZE: usize = 16;
+const KDF_HEADER_SIZE: usize = 12 + SALT_SIZE;
 
 /// Native file format (binary .cdy)
+///
+/// With a password set, the payload is encrypted with AES-256-GCM under a
+/// key derived by Argon2id. The file header, including the KDF parameters
+/// and salt, is authenticated along with it.
 pub struct NativeFormat {
     /// Compression level (0 = none, 1-9 = compression level)
     compression_level: u8,
+    /// Open password
+    password: Option<String>,
+    /// Key derivation costs used when saving
+    #[cfg(feature = "native")]
+    kdf: Argon2Config,
     /// Progress callback
     progress_callback: Option<Box<dyn Fn(usize, usize)>>,
 }
@@ -45,10 +88,37 @@ impl NativeFormat {
     pub fn new() -> Self {
         Self {
             compression_level: 6, // Default moderate compression
+            password: None,
+            #[cfg(feature = "native")]
+            kdf: Argon2Config::default(),
             progress_callback: None,
         }
     }
 
+    /// Require a password to open saved files, and use it when loading
+    pub fn with_password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Set the Argon2id costs used when saving a protected file
+    #[cfg(feature = "native")]
+    pub fn with_kdf(mut self, kdf: Argon2Config) -> Self {
+        self.kdf = kdf;
+        self
+    }
+
+    /// Whether a native file is password protected
+    pub fn is_protected<P: AsRef<Path>>(path: P) -> NativeResult<bool> {
+        let mut header = [0u8; 10];
+        File::open(path)?.read_exact(&mut header)?;
+        if &header[..4] != MAGIC_BYTES {
+            return Err(NativeError::InvalidFormat);
+        }
+        let version = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        Ok(version >= 2 && header[9] != ENCRYPTION_NONE)
+    }
+
     /// Set compression level (0 = none, 1-9 = increasing compression)
     pub fn with_compression(mut self, level: u8) -> Self {
         self.compression_level = level.min(9);
@@ -68,15 +138,29 @@ impl NativeFormat {
     pub fn save<P: AsRef<Path>>(&self, doc: &Document, path: P) -> NativeResult<()> {
         let file = File::create(path)?;
         let mut writer = BufWriter::new(file);
+        self.write_to(doc, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
 
-        // Write magic bytes
-        writer.write_all(MAGIC_BYTES)?;
-
-        // Write version
-        writer.write_all(&CURRENT_VERSION.to_le_bytes())?;
+    /// Save document sealed with the workspace key, for autosaves and
+    /// cached copies that must not be readable off the disk
+    #[cfg(feature = "native")]
+    pub fn save_sealed<P: AsRef<Path>>(&self, doc: &Document, path: P, vault: &WorkspaceVault) -> NativeResult<()> {
+        let mut data = Vec::new();
+        self.write_to(doc, &mut data)?;
+        vault.write(path.as_ref(), &data)?;
+        Ok(())
+    }
 
-        // Write compression flag
-        writer.write_all(&[self.compression_level])?;
+    /// Write document in native binary format
+    pub fn write_to<W: Write>(&self, doc: &Document, writer: &mut W) -> NativeResult<()> {
+        // Magic bytes, version and compression flag; the header is kept
+        // whole so encryption can authenticate it
+        let mut header = Vec::with_capacity(10 + KDF_HEADER_SIZE);
+        header.extend_from_slice(MAGIC_BYTES);
+        header.extend_from_slice(&CURRENT_VERSION.to_le_bytes());
+        header.push(self.compression_level);
 
         // Create file container
         let container = NativeFileContainer {
@@ -100,10 +184,23 @@ impl NativeFormat {
             serialized
         };
 
+        let data = match &self.password {
+            Some(password) => {
+                header.push(ENCRYPTION_AES_GCM);
+                self.encrypt(&data, password, &mut header)?
+            }
+            None => {
+                header.push(ENCRYPTION_NONE);
+                data
+            }
+        };
+
         if let Some(ref callback) = self.progress_callback {
             callback(50, 100);
         }
 
+        writer.write_all(&header)?;
+
         // Write data length
         writer.write_all(&(data.len() as u64).to_le_bytes())?;
 
@@ -120,28 +217,53 @@ impl NativeFormat {
     /// Load document from native binary format
     pub fn load<P: AsRef<Path>>(&self, path: P) -> NativeResult<Document> {
         let file = File::open(path)?;
-        let mut reader = BufReader::new(file);
+        self.read_from(BufReader::new(file))
+    }
 
+    /// Read document in native binary format
+    pub fn read_from<R: Read>(&self, mut reader: R) -> NativeResult<Document> {
         // Read and verify magic bytes
-        let mut magic = [0u8; 4];
-        reader.read_exact(&mut magic)?;
-        if &magic != MAGIC_BYTES {
+        let mut header = vec![0u8; 9];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC_BYTES {
             return Err(NativeError::InvalidFormat);
         }
 
         // Read version
-        let mut version_bytes = [0u8; 4];
-        reader.read_exact(&mut version_bytes)?;
-        let version = u32::from_le_bytes(version_bytes);
+        let version = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
 
         if version > CURRENT_VERSION {
             return Err(NativeError::UnsupportedVersion(version));
         }
 
-        // Read compression flag
-        let mut compression = [0u8; 1];
-        reader.read_exact(&mut compression)?;
-        let is_compressed = compression[0] > 0;
+        // Compression flag
+        let is_compressed = header[8] > 0;
+
+        // Encryption byte and KDF parameters (version 2+)
+        let encryption = if version >= 2 {
+            let mut byte = [0u8; 1];
+            reader.read_exact(&mut byte)?;
+            header.push(byte[0]);
+            byte[0]
+        } else {
+            ENCRYPTION_NONE
+        };
+        match encryption {
+            ENCRYPTION_NONE => {}
+            ENCRYPTION_AES_GCM => {
+                let mut kdf = [0u8; KDF_HEADER_SIZE];
+                reader.read_exact(&mut kdf)?;
+                header.extend_from_slice(&kdf);
+            }
+            _ => return Err(NativeError::InvalidFormat),
+        }
+
+        if version == TILED_VERSION {
+            if encryption != ENCRYPTION_NONE {
+                return Err(NativeError::InvalidFormat);
+            }
+            return crate::io::progressive::read_tiled(&mut reader);
+        }
 
         if let Some(ref callback) = self.progress_callback {
             callback(0, 100);
@@ -160,6 +282,11 @@ impl NativeFormat {
             callback(50, 100);
         }
 
+        if encryption == ENCRYPTION_AES_GCM {
+            let password = self.password.as_deref().ok_or(NativeError::PasswordRequired)?;
+            data = self.decrypt(&data, password, &header)?;
+        }
+
         // Decompress if needed
         let serialized = if is_compressed {
             self.decompress(&data)?
@@ -167,15 +294,74 @@ impl NativeFormat {
             data
         };
 
-        // Deserialize document
-        let container: NativeFileContainer = bincode::deserialize(&serialized)
-            .map_err(|e| NativeError::Deserialization(e.to_string()))?;
+        // Deserialize document, migrating older layouts
+        let document = if version <= LEGACY_VERSION {
+            let container: ContainerV2 = bincode::deserialize(&serialized)
+                .map_err(|e| NativeError::Deserialization(e.to_string()))?;
+            Document::from(container.document)
+        } else {
+            let container: NativeFileContainer = bincode::deserialize(&serialized)
+                .map_err(|e| NativeError::Deserialization(e.to_string()))?;
+            container.document
+        };
 
         if let Some(ref callback) = self.progress_callback {
             callback(100, 100);
         }
 
-        Ok(container.document)
+        Ok(document)
+    }
+
+    /// Encrypt a payload, appending the KDF parameters and salt to `header`
+    #[cfg(feature = "native")]
+    fn encrypt(&self, data: &[u8], password: &str, header: &mut Vec<u8>) -> NativeResult<Vec<u8>> {
+        use rand::{rngs::OsRng, RngCore};
+
+        let mut salt = [0u8; SALT_SIZE];
+        OsRng.fill_bytes(&mut salt);
+        header.extend_from_slice(&self.kdf.memory_cost.to_le_bytes());
+        header.extend_from_slice(&self.kdf.time_cost.to_le_bytes());
+        header.extend_from_slice(&self.kdf.parallelism.to_le_bytes());
+        header.extend_from_slice(&salt);
+
+        let encrypted = cipher(password, &salt, &self.kdf)?
+            .encrypt(data, Some(header.as_slice()))
+            .map_err(|e| NativeError::Encryption(e.to_string()))?;
+        Ok(encrypted.to_bytes())
+    }
+
+    #[cfg(not(feature = "native"))]
+    fn encrypt(&self, _data: &[u8], _password: &str, _header: &mut Vec<u8>) -> NativeResult<Vec<u8>> {
+        Err(NativeError::Encryption(
+            "password protection requires the native feature".to_string(),
+        ))
+    }
+
+    /// Decrypt a payload using the KDF parameters at the end of `header`
+    #[cfg(feature = "native")]
+    fn decrypt(&self, data: &[u8], password: &str, header: &[u8]) -> NativeResult<Vec<u8>> {
+        let kdf = &header[header.len() - KDF_HEADER_SIZE..];
+        let cost = |at: usize| u32::from_le_bytes([kdf[at], kdf[at + 1], kdf[at + 2], kdf[at + 3]]);
+        let config = Argon2Config {
+            memory_cost: cost(0),
+            time_cost: cost(4),
+            parallelism: cost(8),
+            ..Argon2Config::default()
+        };
+
+        let mut encrypted = EncryptedData::from_bytes(data, Aes256GcmCipher::NONCE_SIZE)
+            .map_err(|_| NativeError::Decryption)?;
+        encrypted.associated_data = header.to_vec();
+        cipher(password, &kdf[12..], &config)?
+            .decrypt(&encrypted)
+            .map_err(|_| NativeError::Decryption)
+    }
+
+    #[cfg(not(feature = "native"))]
+    fn decrypt(&self, _data: &[u8], _password: &str, _header: &[u8]) -> NativeResult<Vec<u8>> {
+        Err(NativeError::Encryption(
+            "password protection requires the native feature".to_string(),
+        ))
     }
 
     /// Compress data using simple run-length encoding (placeholder for real compression)
@@ -207,6 +393,13 @@ impl NativeFormat {
     }
 }
 
+#[cfg(feature = "native")]
+fn cipher(password: &str, salt: &[u8], kdf: &Argon2Config) -> NativeResult<Aes256GcmCipher> {
+    let key = KdfProvider::derive_argon2id(password.as_bytes(), salt, kdf)
+        .map_err(|e| NativeError::Encryption(e.to_string()))?;
+    Aes256GcmCipher::new(key.as_bytes()).map_err(|e| NativeError::Encryption(e.to_string()))
+}
+
 impl Default for NativeFormat {
     fn default() -> Self {
         Self::new()
@@ -248,7 +441,7 @@ impl JsonFormat {
     /// Save document to JSON format
     pub fn save<P: AsRef<Path>>(&self, doc: &Document, path: P) -> NativeResult<()> {
         let file = File::create(path)?;
-        let _writer = BufWriter::new(file);
+        let writer = BufWriter::new(file);
 
         if let Some(ref callback) = self.progress_callback {
             callback(0, 100);
@@ -282,7 +475,7 @@ impl JsonFormat {
     /// Load document from JSON format
     pub fn load<P: AsRef<Path>>(&self, path: P) -> NativeResult<Document> {
         let file = File::open(path)?;
-        let _reader = BufReader::new(file);
+        let reader = BufReader::new(file);
 
         if let Some(ref callback) = self.progress_callback {
             callback(0, 100);
@@ -332,18 +525,18 @@ impl Default for JsonFormat {
 
 /// File container with version information
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct NativeFileContainer {
+pub(crate) struct NativeFileContainer {
     /// File format version
-    version: u32,
+    pub(crate) version: u32,
     /// The document data
-    document: Document,
+    pub(crate) document: Document,
     /// File metadata
-    metadata: FileMetadata,
+    pub(crate) metadata: FileMetadata,
 }
 
 /// File metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct FileMetadata {
+pub(crate) struct FileMetadata {
     /// CADDY version that created the file
     caddy_version: String,
     /// Timestamp when file was created
@@ -353,7 +546,7 @@ struct FileMetadata {
 }
 
 impl FileMetadata {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             caddy_version: env!("CARGO_PKG_VERSION").to_string(),
             created: chrono::Utc::now(),
@@ -399,10 +592,39 @@ impl FormatDetector {
 
     /// Load a document with automatic format detection
     pub fn load<P: AsRef<Path>>(path: P) -> NativeResult<Document> {
+        Self::load_native(path, NativeFormat::new())
+    }
+
+    /// Load a document, opening password-protected native files with
+    /// `password`
+    pub fn load_with_password<P: AsRef<Path>>(path: P, password: &str) -> NativeResult<Document> {
+        Self::load_native(path, NativeFormat::new().with_password(password))
+    }
+
+    /// Load a document, decrypting it first if it was sealed with the
+    /// workspace key; unsealed files load as usual
+    #[cfg(feature = "native")]
+    pub fn load_with_vault<P: AsRef<Path>>(path: P, vault: &WorkspaceVault) -> NativeResult<Document> {
+        if !WorkspaceVault::is_sealed_file(path.as_ref())? {
+            return Self::load(path);
+        }
+
+        let data = vault.read(path.as_ref())?;
+        if data.starts_with(MAGIC_BYTES) {
+            NativeFormat::new().read_from(data.as_slice())
+        } else if data.first() == Some(&b'{') {
+            let json = std::str::from_utf8(&data).map_err(|e| NativeError::Deserialization(e.to_string()))?;
+            JsonFormat::new().from_string(json)
+        } else {
+            Err(NativeError::InvalidFormat)
+        }
+    }
+
+    fn load_native<P: AsRef<Path>>(path: P, native: NativeFormat) -> NativeResult<Document> {
         let format = Self::detect(&path)?;
 
         match format {
-            FileFormat::NativeBinary => NativeFormat::new().load(path),
+            FileFormat::NativeBinary => native.load(path),
             FileFormat::NativeJson => JsonFormat::new().load(path),
             FileFormat::Dxf => {
                 use crate::io::dxf::DxfReader;
@@ -564,7 +786,7 @@ mod tests {
 
     #[test]
     fn test_native_format_roundtrip() {
-        let _doc = Document::new();
+        let doc = Document::new();
         let format = NativeFormat::new();
 
         let path = std::env::temp_dir().join("test.cdy");
@@ -577,7 +799,7 @@ mod tests {
 
     #[test]
     fn test_json_format_roundtrip() {
-        let _doc = Document::new();
+        let doc = Document::new();
         let format = JsonFormat::new();
 
         let json = format.to_string(&doc).unwrap();
@@ -586,6 +808,178 @@ mod tests {
         assert_eq!(doc.id, loaded.id);
     }
 
+    #[test]
+    fn test_reads_version_2_layout() {
+        use crate::io::legacy::*;
+        use crate::io::units::Unit;
+        use std::collections::HashMap;
+        use uuid::Uuid;
+
+        let defaults = Document::new();
+        let hatch = EntityV2 {
+            id: Uuid::new_v4(),
+            layer: "0".to_string(),
+            color: None,
+            line_type: None,
+            line_weight: None,
+            visible: true,
+            geometry: GeometryV2::Hatch(HatchV2 {
+                pattern: "ANSI31".to_string(),
+                scale: 2.0,
+                angle: 45.0,
+                boundaries: vec![vec![
+                    Vec3::new(0.0, 0.0, 0.0),
+                    Vec3::new(10.0, 0.0, 0.0),
+                    Vec3::new(0.0, 10.0, 0.0),
+                ]],
+            }),
+            attributes: HashMap::new(),
+        };
+        let layer = LayerV2 {
+            name: "Walls".to_string(),
+            color: Color::new(255, 0, 0),
+            line_type: LineType::Continuous,
+            line_weight: LineWeight::Default,
+            visible: true,
+            locked: true,
+            frozen: false,
+            plottable: true,
+        };
+        let grid = &defaults.settings.grid;
+        let snap = &defaults.settings.snap;
+        let old = ContainerV2 {
+            version: 2,
+            document: DocumentV2 {
+                id: Uuid::new_v4(),
+                metadata: defaults.metadata.clone(),
+                settings: SettingsV2 {
+                    units: Unit::Inches,
+                    precision: defaults.settings.precision,
+                    paper_size: PaperSize::A3,
+                    background_color: Color::new(0, 0, 0),
+                    grid: GridV2 {
+                        enabled: true,
+                        major_spacing: 12.0,
+                        minor_spacing: 1.0,
+                        color: grid.color,
+                        snap: true,
+                    },
+                    snap: SnapV2 {
+                        enabled: true,
+                        endpoint: true,
+                        midpoint: false,
+                        center: true,
+                        intersection: true,
+                        perpendicular: true,
+                        tangent: true,
+                        threshold: snap.threshold,
+                    },
+                    autosave_interval: None,
+                    create_backup: false,
+                },
+                entities: vec![hatch],
+                layers: HashMap::from([("Walls".to_string(), layer)]),
+                blocks: HashMap::new(),
+                views: HashMap::new(),
+                variables: HashMap::from([("client".to_string(), "ACME".to_string())]),
+            },
+            metadata: FileMetadata::new(),
+        };
+
+        // Version 1 header: magic, version, no compression, length, payload
+        let payload = bincode::serialize(&old).unwrap();
+        let mut bytes = MAGIC_BYTES.to_vec();
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.push(0);
+        bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&payload);
+
+        let loaded = NativeFormat::new().read_from(bytes.as_slice()).unwrap();
+        assert_eq!(loaded.id, old.document.id);
+        assert_eq!(loaded.settings.units, Unit::Inches);
+        assert!(loaded.settings.grid.snap && loaded.settings.grid.adaptive);
+        assert!(!loaded.settings.snap.midpoint);
+        assert_eq!(loaded.settings.snap.polar_increment, 45.0);
+        assert!(loaded.layers["Walls"].locked && !loaded.layers["Walls"].confidential);
+        assert_eq!(loaded.variables["client"], "ACME");
+        match &loaded.entities[0].geometry {
+            GeometryType::Hatch(h) => {
+                assert_eq!((h.scale, h.angle), (2.0, 45.0));
+                assert!(h.definition.is_none() && h.associated.is_empty());
+            }
+            other => panic!("expected a hatch, got {}", other.type_name()),
+        }
+        assert_eq!(loaded.entities_in_box(&loaded.entities[0].bounding_box()).len(), 1);
+
+        // New files are written at the current version, which older builds reject
+        let mut saved = Vec::new();
+        NativeFormat::new().write_to(&loaded, &mut saved).unwrap();
+        assert_eq!(u32::from_le_bytes([saved[4], saved[5], saved[6], saved[7]]), CURRENT_VERSION);
+        let reloaded = NativeFormat::new().read_from(saved.as_slice()).unwrap();
+        assert_eq!(reloaded.layers["Walls"].color, Color::new(255, 0, 0));
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn test_password_protection() {
+        use crate::enterprise::crypto::kdf::Argon2Config;
+
+        let doc = Document::new();
+        let dir = std::env::temp_dir();
+        let plain = dir.join(format!("plain-{}.cdy", doc.id));
+        let protected = dir.join(format!("protected-{}.cdy", doc.id));
+
+        NativeFormat::new().save(&doc, &plain).unwrap();
+        assert!(!NativeFormat::is_protected(&plain).unwrap());
+
+        NativeFormat::new()
+            .with_password("site-plan")
+            .with_kdf(Argon2Config::low_memory())
+            .save(&doc, &protected)
+            .unwrap();
+        assert!(NativeFormat::is_protected(&protected).unwrap());
+        assert!(matches!(FormatDetector::load(&protected), Err(NativeError::PasswordRequired)));
+        assert!(matches!(
+            NativeFormat::new().with_password("guess").load(&protected),
+            Err(NativeError::Decryption)
+        ));
+        let loaded = FormatDetector::load_with_password(&protected, "site-plan").unwrap();
+        assert_eq!(loaded.id, doc.id);
+
+        // The KDF parameters are authenticated with the payload
+        let mut bytes = std::fs::read(&protected).unwrap();
+        bytes[14] ^= 1;
+        std::fs::write(&protected, bytes).unwrap();
+        assert!(NativeFormat::new().with_password("site-plan").load(&protected).is_err());
+
+        std::fs::remove_file(plain).ok();
+        std::fs::remove_file(protected).ok();
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn test_sealed_autosave() {
+        use crate::enterprise::crypto::kdf::Argon2Config;
+        use crate::io::vault::MemoryKeychain;
+
+        let doc = Document::new();
+        let dir = std::env::temp_dir().join(format!("workspace-{}", doc.id));
+        let vault = WorkspaceVault::open_with(&MemoryKeychain::new(), "alice", &dir, &Argon2Config::low_memory())
+            .unwrap();
+
+        let path = dir.join("plan.cdy");
+        NativeFormat::new().save_sealed(&doc, &path, &vault).unwrap();
+        assert!(matches!(FormatDetector::load(&path), Err(NativeError::InvalidFormat)));
+        assert_eq!(FormatDetector::load_with_vault(&path, &vault).unwrap().id, doc.id);
+
+        // Unsealed files open the same way
+        let plain = dir.join("plain.cdy");
+        NativeFormat::new().save(&doc, &plain).unwrap();
+        assert_eq!(FormatDetector::load_with_vault(&plain, &vault).unwrap().id, doc.id);
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+
     #[test]
     fn test_format_detection() {
         use std::io::Write;
@@ -17,6 +17,8 @@
 //! - Smooth shading groups
 //! - Free-form geometry (curves and surfaces)
 
+use crate::core::primitives::Point3;
+use crate::geometry::mesh::{HalfEdgeMesh, MeshError, MeshResult, TriangleFace, TriangleMesh, Vertex};
 use crate::io::document::*;
 use std::collections::HashMap;
 use std::fs::File;
@@ -174,6 +176,55 @@ impl ObjMesh {
             group_name: None,
         }
     }
+
+    /// Half-edge mesh of the faces, for adjacency queries and repair
+    ///
+    /// Faces keep their polygons; negative (relative) indices count back
+    /// from the last vertex.
+    pub fn to_half_edge_mesh(&self) -> MeshResult<HalfEdgeMesh> {
+        let faces = self.face_indices()?;
+        HalfEdgeMesh::from_polygons(self.positions(), &faces)
+    }
+
+    /// Triangle mesh of the faces, polygons split into fans, for repair
+    /// and STL export
+    pub fn to_triangle_mesh(&self) -> MeshResult<TriangleMesh> {
+        let faces = self
+            .face_indices()?
+            .iter()
+            .flat_map(|polygon| {
+                (1..polygon.len().saturating_sub(1))
+                    .map(move |k| TriangleFace::new(polygon[0], polygon[k], polygon[k + 1]))
+            })
+            .collect();
+        let vertices = self.positions().into_iter().map(Vertex::new).collect();
+        Ok(TriangleMesh::from_data(vertices, faces))
+    }
+
+    fn positions(&self) -> Vec<Point3> {
+        self.vertices.iter().map(|v| v.to_vec3().to_point3()).collect()
+    }
+
+    /// Zero-based vertex indices of each face
+    fn face_indices(&self) -> MeshResult<Vec<Vec<usize>>> {
+        let count = self.vertices.len() as i64;
+        self.faces
+            .iter()
+            .map(|face| {
+                face.vertices
+                    .iter()
+                    .map(|fv| {
+                        let index = fv.vertex_index as i64;
+                        let resolved = if index < 0 { count + index } else { index - 1 };
+                        usize::try_from(resolved)
+                            .ok()
+                            .filter(|&i| i < self.vertices.len())
+                            .ok_or(MeshError::InvalidVertex(index.unsigned_abs() as usize))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
 }
 
 /// OBJ file reader
@@ -213,7 +264,7 @@ impl ObjReader {
         });
 
         let file = File::open(path_ref)?;
-        let _reader = BufReader::new(file);
+        let reader = BufReader::new(file);
         self.read(reader, &base_path)
     }
 
@@ -405,7 +456,7 @@ impl ObjReader {
 
     fn load_mtl_file(&self, path: &Path) -> ObjResult<HashMap<String, ObjMaterial>> {
         let file = File::open(path)?;
-        let _reader = BufReader::new(file);
+        let reader = BufReader::new(file);
         let mut materials = HashMap::new();
         let mut current_material: Option<ObjMaterial> = None;
 
@@ -600,7 +651,7 @@ impl ObjWriter {
         // Write faces (grouped by material)
         let mut current_material: Option<&String> = None;
         for face in &mesh.faces {
-            if face.material != current_material.map(|s| s.as_str()) {
+            if face.material.as_deref() != current_material.map(|s| s.as_str()) {
                 if let Some(ref mat) = face.material {
                     writeln!(writer, "usemtl {}", mat)?;
                     current_material = Some(mat);
@@ -676,4 +727,32 @@ mod tests {
         assert_eq!(mat.transparency, 1.0);
         assert_eq!(mat.illumination_model, 2);
     }
+
+    #[test]
+    fn test_quads_to_repaired_triangles() {
+        // Open box: five quads of a unit cube, no lid
+        let mut mesh = ObjMesh::new();
+        for i in 0..8 {
+            mesh.vertices.push(ObjVertex::new((i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64));
+        }
+        for quad in [[1, 3, 4, 2], [1, 2, 6, 5], [3, 7, 8, 4], [1, 5, 7, 3], [2, 4, 8, 6]] {
+            mesh.faces.push(ObjFace {
+                vertices: quad
+                    .iter()
+                    .map(|&i| FaceVertex { vertex_index: i, texcoord_index: None, normal_index: None })
+                    .collect(),
+                material: None,
+            });
+        }
+
+        let mut triangles = mesh.to_triangle_mesh().unwrap();
+        assert_eq!(triangles.faces.len(), 10);
+        let report = crate::geometry::repair::MeshRepair::new().repair(&mut triangles);
+        assert_eq!(report.before.holes, 1);
+        assert_eq!(report.added_faces, 2);
+        assert!(report.after.is_watertight());
+
+        mesh.faces[0].vertices[0].vertex_index = 9;
+        assert_eq!(mesh.to_triangle_mesh().unwrap_err(), MeshError::InvalidVertex(9));
+    }
 }
@@ -0,0 +1,380 @@
+// CADDY - Enterprise CAD System
+// File I/O System - Document Parameters Module
+
+//! Document parameters
+//!
+//! A drawing carries a table of named parameters, each an expression with an
+//! optional length unit:
+//!
+//! ```text
+//! width     = 1200            mm
+//! height    = width * 0.75    mm
+//! spacing   = 300             mm
+//! bays      = floor(width / spacing)
+//! ```
+//!
+//! Expressions use the takeoff [`Formula`] syntax and may read other
+//! parameters in any order, as long as there are no cycles. Lengths are
+//! converted when read: a parameter in millimeters reading one in inches
+//! sees it in millimeters, and a unitless parameter sees lengths in drawing
+//! units. Literal lengths convert the same way, so `depth = 2ft + 30mm`
+//! works in any unit.
+//!
+//! The same evaluation backs every numeric input:
+//! [`ParameterValues::evaluate`] takes what was typed into a command prompt,
+//! property or dynamic input field (`25.4mm*3 + 1in`, `sqrt(2)*10`,
+//! `width / 2`), and [`ParameterValues::empty`] serves where there is no
+//! document.
+//!
+//! Evaluating the table gives [`ParameterValues`], which drive dimensional
+//! constraints (converted to a `ParameterTable`), array counts and `PARAM.`
+//! text fields, so a template redrawn with different parameter values
+//! updates its geometry, arrays and title block together.
+
+use crate::io::units::Unit;
+use crate::takeoff::formula::Formula;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use thiserror::Error;
+
+/// Parameter errors
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ParameterError {
+    #[error("Invalid parameter name: '{0}'")]
+    InvalidName(String),
+    #[error("Parameter '{0}' is defined twice")]
+    Duplicate(String),
+    #[error("Invalid expression for '{name}': {message}")]
+    Expression { name: String, message: String },
+    #[error("'{parameter}' reads unknown parameter '{reference}'")]
+    UnknownReference { parameter: String, reference: String },
+    #[error("Parameters depend on each other: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+    #[error("'{expression}' is not a count: {value}")]
+    NotACount { expression: String, value: f64 },
+}
+
+pub type ParameterResult<T> = Result<T, ParameterError>;
+
+/// Named value in the document parameter table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Parameter {
+    /// Name expressions and fields refer to it by
+    pub name: String,
+    /// Value, as an expression over other parameters
+    pub expression: Formula,
+    /// Length unit of the value; `None` for counts, ratios and angles
+    pub unit: Option<Unit>,
+    /// Note shown in the parameter editor
+    #[serde(default)]
+    pub description: String,
+}
+
+/// The document parameter table, in the order parameters were added
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Parameters {
+    parameters: Vec<Parameter>,
+}
+
+impl Parameters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.parameters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parameters.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Parameter> {
+        self.parameters.iter()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Parameter> {
+        self.parameters.iter().find(|p| p.name == name)
+    }
+
+    /// Add a parameter, or change the expression and unit of an existing one
+    ///
+    /// References to other parameters are checked when evaluating, so
+    /// parameters can be added in any order.
+    pub fn set(&mut self, name: &str, expression: &str, unit: Option<Unit>) -> ParameterResult<()> {
+        if !is_identifier(name) {
+            return Err(ParameterError::InvalidName(name.to_string()));
+        }
+        let expression = Formula::parse(expression).map_err(|e| ParameterError::Expression {
+            name: name.to_string(),
+            message: e.to_string(),
+        })?;
+        match self.parameters.iter_mut().find(|p| p.name == name) {
+            Some(parameter) => {
+                parameter.expression = expression;
+                parameter.unit = unit;
+            }
+            None => self.parameters.push(Parameter {
+                name: name.to_string(),
+                expression,
+                unit,
+                description: String::new(),
+            }),
+        }
+        Ok(())
+    }
+
+    /// Set a parameter's description; `false` if there is no such parameter
+    pub fn describe(&mut self, name: &str, description: impl Into<String>) -> bool {
+        match self.parameters.iter_mut().find(|p| p.name == name) {
+            Some(parameter) => {
+                parameter.description = description.into();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Parameter> {
+        let index = self.parameters.iter().position(|p| p.name == name)?;
+        Some(self.parameters.remove(index))
+    }
+
+    /// Parameters that read `name`, directly
+    pub fn dependents(&self, name: &str) -> Vec<&str> {
+        self.parameters
+            .iter()
+            .filter(|p| p.expression.variables().iter().any(|v| v == name))
+            .map(|p| p.name.as_str())
+            .collect()
+    }
+
+    /// Evaluate every parameter; unitless parameters read lengths in `drawing_units`
+    pub fn evaluate(&self, drawing_units: Unit) -> ParameterResult<ParameterValues> {
+        let mut values = ParameterValues {
+            values: BTreeMap::new(),
+            drawing_units,
+        };
+        for index in self.evaluation_order()? {
+            let parameter = &self.parameters[index];
+            let target = parameter.unit.unwrap_or(drawing_units);
+            let inputs: HashMap<String, f64> = parameter
+                .expression
+                .variables()
+                .into_iter()
+                .filter_map(|v| values.value_in(&v, target).map(|value| (v, value)))
+                .collect();
+            let value =
+                parameter.expression.evaluate_in(&inputs, target).map_err(|e| ParameterError::Expression {
+                    name: parameter.name.clone(),
+                    message: e.to_string(),
+                })?;
+            values.values.insert(parameter.name.clone(), (value, parameter.unit));
+        }
+        Ok(values)
+    }
+
+    /// Indices of the parameters, each after the ones it reads
+    fn evaluation_order(&self) -> ParameterResult<Vec<usize>> {
+        let index: HashMap<&str, usize> = self
+            .parameters
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p.name.as_str(), i))
+            .collect();
+        let mut reads = Vec::with_capacity(self.parameters.len());
+        for parameter in &self.parameters {
+            let mut inputs = Vec::new();
+            for reference in parameter.expression.variables() {
+                match index.get(reference.as_str()) {
+                    Some(&i) => inputs.push(i),
+                    None => {
+                        return Err(ParameterError::UnknownReference {
+                            parameter: parameter.name.clone(),
+                            reference,
+                        })
+                    }
+                }
+            }
+            reads.push(inputs);
+        }
+
+        // Depth-first, keeping the path so a cycle can be reported in full
+        let mut order = Vec::with_capacity(self.parameters.len());
+        let mut done = HashSet::new();
+        for root in 0..self.parameters.len() {
+            let mut path = vec![root];
+            let mut next = vec![0usize];
+            while let Some(&current) = path.last() {
+                if done.contains(&current) {
+                    path.pop();
+                    next.pop();
+                    continue;
+                }
+                let cursor = next.last_mut().expect("cursor per path entry");
+                match reads[current].get(*cursor).copied() {
+                    Some(input) => {
+                        *cursor += 1;
+                        if let Some(start) = path.iter().position(|&p| p == input) {
+                            let mut names: Vec<String> =
+                                path[start..].iter().map(|&i| self.parameters[i].name.clone()).collect();
+                            names.push(self.parameters[input].name.clone());
+                            return Err(ParameterError::Cycle(names));
+                        }
+                        if !done.contains(&input) {
+                            path.push(input);
+                            next.push(0);
+                        }
+                    }
+                    None => {
+                        done.insert(current);
+                        order.push(current);
+                        path.pop();
+                        next.pop();
+                    }
+                }
+            }
+        }
+        Ok(order)
+    }
+}
+
+/// Evaluated parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterValues {
+    /// Value and unit of each parameter
+    values: BTreeMap<String, (f64, Option<Unit>)>,
+    drawing_units: Unit,
+}
+
+impl ParameterValues {
+    /// No parameters, with lengths in `drawing_units`
+    pub fn empty(drawing_units: Unit) -> Self {
+        Self {
+            values: BTreeMap::new(),
+            drawing_units,
+        }
+    }
+
+    /// Value in the parameter's own unit
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.values.get(name).map(|(value, _)| *value)
+    }
+
+    pub fn unit(&self, name: &str) -> Option<Unit> {
+        self.values.get(name).and_then(|(_, unit)| *unit)
+    }
+
+    /// Value converted to `unit`; unitless parameters are returned as is
+    pub fn value_in(&self, name: &str, unit: Unit) -> Option<f64> {
+        self.values.get(name).map(|(value, own)| match own {
+            Some(own) => own.convert_to(*value, unit),
+            None => *value,
+        })
+    }
+
+    /// Evaluate an expression over the parameters, with lengths in drawing units
+    pub fn evaluate(&self, expression: &str) -> ParameterResult<f64> {
+        let invalid = |message: String| ParameterError::Expression {
+            name: expression.to_string(),
+            message,
+        };
+        let formula = Formula::parse(expression).map_err(|e| invalid(e.to_string()))?;
+        formula.evaluate_in(&self.inputs(), self.drawing_units).map_err(|e| invalid(e.to_string()))
+    }
+
+    /// Evaluate an expression that must give a whole number of at least one,
+    /// such as an array count
+    pub fn count(&self, expression: &str) -> ParameterResult<usize> {
+        let value = self.evaluate(expression)?;
+        let rounded = value.round();
+        if !value.is_finite() || rounded < 1.0 || (value - rounded).abs() > 1e-9 {
+            return Err(ParameterError::NotACount {
+                expression: expression.to_string(),
+                value,
+            });
+        }
+        Ok(rounded as usize)
+    }
+
+    /// Parameter formatted for display, e.g. in a text field
+    pub fn format(&self, name: &str, precision: Option<usize>) -> Option<String> {
+        let (value, unit) = self.values.get(name)?;
+        Some(match unit {
+            Some(unit) => unit.format(*value, precision),
+            None => match precision {
+                Some(precision) => format!("{:.*}", precision, value),
+                None => value.to_string(),
+            },
+        })
+    }
+
+    /// Parameter values in drawing units
+    pub(crate) fn inputs(&self) -> HashMap<String, f64> {
+        self.values
+            .keys()
+            .filter_map(|name| Some((name.clone(), self.value_in(name, self.drawing_units)?)))
+            .collect()
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_in_dependency_order_with_units() {
+        let mut params = Parameters::new();
+        params.set("bays", "floor(width / spacing)", None).unwrap();
+        params.set("height", "width * 0.75", Some(Unit::Millimeters)).unwrap();
+        params.set("width", "1.2", Some(Unit::Meters)).unwrap();
+        params.set("spacing", "300", Some(Unit::Millimeters)).unwrap();
+        params.set("depth", "2ft + 30mm", Some(Unit::Millimeters)).unwrap();
+
+        let values = params.evaluate(Unit::Millimeters).unwrap();
+        assert!((values.get("height").unwrap() - 900.0).abs() < 1e-9);
+        assert_eq!(values.get("bays"), Some(4.0));
+        assert_eq!(values.count("bays + 1").unwrap(), 5);
+        assert!((values.evaluate("width - spacing").unwrap() - 900.0).abs() < 1e-9);
+        assert_eq!(values.format("spacing", Some(0)).as_deref(), Some("300 mm"));
+        assert!(matches!(values.count("width / 7"), Err(ParameterError::NotACount { .. })));
+
+        // Typed input: literal lengths convert to drawing units
+        assert!((values.get("depth").unwrap() - 639.6).abs() < 1e-9);
+        assert!((values.evaluate("25.4mm*3 + 1in").unwrap() - 101.6).abs() < 1e-9);
+        assert!((values.evaluate("width/2 + 1cm").unwrap() - 610.0).abs() < 1e-9);
+        assert!((ParameterValues::empty(Unit::Inches).evaluate("50.8mm").unwrap() - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_invalid_tables() {
+        let mut params = Parameters::new();
+        assert!(matches!(params.set("2x", "1", None), Err(ParameterError::InvalidName(_))));
+        assert!(matches!(params.set("a", "1 +", None), Err(ParameterError::Expression { .. })));
+
+        params.set("a", "b + 1", None).unwrap();
+        assert!(matches!(
+            params.evaluate(Unit::Millimeters),
+            Err(ParameterError::UnknownReference { .. })
+        ));
+
+        params.set("b", "c * 2", None).unwrap();
+        params.set("c", "a", None).unwrap();
+        assert_eq!(
+            params.evaluate(Unit::Millimeters),
+            Err(ParameterError::Cycle(vec![
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string(),
+                "a".to_string()
+            ]))
+        );
+        assert_eq!(params.dependents("a"), vec!["c"]);
+    }
+}
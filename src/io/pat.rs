@@ -0,0 +1,176 @@
+// CADDY - Enterprise CAD System
+// File I/O System - AutoCAD Hatch Pattern (.pat) Support
+
+//! # Hatch Pattern Files
+//!
+//! Reads and writes AutoCAD `.pat` hatch pattern libraries. A file holds
+//! any number of patterns, each a header line followed by one line per
+//! line family:
+//!
+//! ```text
+//! ; comment
+//! *NAME, optional description
+//! angle, x-origin, y-origin, delta-x, delta-y [, dash-1, dash-2, ...]
+//! ```
+//!
+//! Angles are in degrees in the file and radians in [`HatchPattern`];
+//! dashes are positive for pen-down, negative for pen-up and zero for dots.
+
+use crate::geometry::hatch::{HatchPattern, PatternLine};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+use thiserror::Error;
+
+/// Pattern file errors
+#[derive(Error, Debug)]
+pub enum PatError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Parse error at line {line}: {message}")]
+    Parse { line: usize, message: String },
+}
+
+pub type PatResult<T> = Result<T, PatError>;
+
+/// Parse the patterns in a `.pat` file's text
+pub fn parse_pat(text: &str) -> PatResult<Vec<HatchPattern>> {
+    let mut patterns: Vec<HatchPattern> = Vec::new();
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        // Comments run to the end of the line
+        let content = raw.split(';').next().unwrap_or("").trim();
+        if content.is_empty() {
+            continue;
+        }
+
+        if let Some(header) = content.strip_prefix('*') {
+            let (name, description) = header.split_once(',').unwrap_or((header, ""));
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(PatError::Parse {
+                    line,
+                    message: "pattern without a name".to_string(),
+                });
+            }
+            patterns.push(HatchPattern {
+                name: name.to_uppercase(),
+                description: description.trim().to_string(),
+                lines: Vec::new(),
+            });
+            continue;
+        }
+
+        let Some(pattern) = patterns.last_mut() else {
+            return Err(PatError::Parse {
+                line,
+                message: "line family before the first *NAME header".to_string(),
+            });
+        };
+        let values = content
+            .split(',')
+            .map(|v| v.trim().parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PatError::Parse {
+                line,
+                message: e.to_string(),
+            })?;
+        if values.len() < 5 {
+            return Err(PatError::Parse {
+                line,
+                message: format!("expected at least 5 values, found {}", values.len()),
+            });
+        }
+        pattern.lines.push(PatternLine {
+            angle: values[0].to_radians(),
+            origin: [values[1], values[2]],
+            offset: [values[3], values[4]],
+            dashes: values[5..].to_vec(),
+        });
+    }
+    Ok(patterns)
+}
+
+/// Read a `.pat` file
+pub fn read_pat_file(path: impl AsRef<Path>) -> PatResult<Vec<HatchPattern>> {
+    parse_pat(&fs::read_to_string(path)?)
+}
+
+/// Write patterns as `.pat` text
+pub fn write_pat(patterns: &[HatchPattern]) -> String {
+    let mut out = String::new();
+    for pattern in patterns {
+        if pattern.description.is_empty() {
+            let _ = writeln!(out, "*{}", pattern.name);
+        } else {
+            let _ = writeln!(out, "*{}, {}", pattern.name, pattern.description);
+        }
+        for family in &pattern.lines {
+            let mut values = vec![
+                family.angle.to_degrees(),
+                family.origin[0],
+                family.origin[1],
+                family.offset[0],
+                family.offset[1],
+            ];
+            values.extend(&family.dashes);
+            let values: Vec<String> = values.iter().map(|v| format_value(*v)).collect();
+            let _ = writeln!(out, "{}", values.join(", "));
+        }
+    }
+    out
+}
+
+/// Shortest text that reads back as the same value, trimmed of noise from
+/// the degree conversion
+fn format_value(value: f64) -> String {
+    let rounded = (value * 1e9).round() / 1e9;
+    if rounded == 0.0 {
+        "0".to_string()
+    } else {
+        rounded.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BRICK: &str = "\
+; Two families: courses and staggered joints
+*BRICK, Brick or masonry-type surface
+0, 0,0, 0,6.35
+90, 0,0, 6.35,6.35, 6.35,-6.35 ; joints
+*ansi31
+45, 0,0, 0,3.175
+";
+
+    #[test]
+    fn test_parse_and_round_trip() {
+        let patterns = parse_pat(BRICK).unwrap();
+        assert_eq!(patterns.len(), 2);
+        assert_eq!(patterns[0].name, "BRICK");
+        assert_eq!(patterns[0].description, "Brick or masonry-type surface");
+        assert_eq!(patterns[0].lines.len(), 2);
+        assert!((patterns[0].lines[1].angle - std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+        assert_eq!(patterns[0].lines[1].dashes, vec![6.35, -6.35]);
+        assert_eq!(patterns[1].name, "ANSI31");
+        assert_eq!(patterns[1], HatchPattern::builtin("ANSI31").unwrap());
+
+        let text = write_pat(&patterns);
+        assert!(text.starts_with("*BRICK, Brick or masonry-type surface\n0, 0, 0, 0, 6.35\n90,"));
+        assert_eq!(parse_pat(&text).unwrap(), patterns);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_pat("45, 0,0, 0,3.175\n").unwrap_err();
+        assert!(matches!(err, PatError::Parse { line: 1, .. }));
+        let err = parse_pat("*A\n45, 0, 0\n").unwrap_err();
+        assert!(matches!(err, PatError::Parse { line: 2, .. }));
+        let err = parse_pat("*A\n\n45, 0, 0, x, 1\n").unwrap_err();
+        assert!(matches!(err, PatError::Parse { line: 3, .. }));
+    }
+}
@@ -0,0 +1,627 @@
+// CADDY - Enterprise CAD System
+// File I/O System - Export Pipeline Module
+// Agent 6 - File I/O System Developer
+
+//! Export pipelines with per-rule format routing
+//!
+//! An [`ExportPipeline`] is a list of rules, each sending the entities an
+//! [`ExportSelector`] picks out (by layer, inserted block, classification,
+//! tag or entity type) to one output file in one format: structural layers to DXF,
+//! presentation layers to PDF, and so on. Pipelines are saved as JSON and
+//! reused across drawings. Running one writes every output in a single pass
+//! and reports each rule's outcome, so one failing output doesn't stop the
+//! others.
+//!
+//! Rules are independent: an entity can be routed to several outputs, and
+//! entities no rule selects are counted in the report.
+
+use crate::io::document::{Document, Entity, GeometryType};
+use crate::io::dxf::{DxfVersion, DxfWriter};
+use crate::io::export::{PdfExportSettings, PdfExporter, RasterExportSettings, RasterExporter, SvgExporter};
+use crate::io::native::{JsonFormat, NativeFormat};
+use crate::io::redaction::RedactionProfile;
+use crate::io::step::{ApplicationProtocol, StepWriter};
+use crate::io::tags;
+use crate::standards::matches_pattern;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Entity attribute holding its classification (e.g. `Structural`)
+pub const CLASSIFICATION_ATTRIBUTE: &str = "CLASSIFICATION";
+
+/// Pipeline errors
+#[derive(Error, Debug)]
+pub enum PipelineError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid pipeline file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid rule: {0}")]
+    InvalidRule(String),
+}
+
+pub type PipelineResult<T> = Result<T, PipelineError>;
+
+/// Output format of a rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PipelineFormat {
+    /// DXF R2018
+    Dxf,
+    Svg,
+    Pdf,
+    Png,
+    Tiff,
+    /// STEP AP214
+    Step,
+    /// Native binary (.cdy)
+    Native,
+    /// Native JSON (.cdyj)
+    Json,
+}
+
+impl PipelineFormat {
+    /// Format for a file extension
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
+            "dxf" => Some(Self::Dxf),
+            "svg" => Some(Self::Svg),
+            "pdf" => Some(Self::Pdf),
+            "png" => Some(Self::Png),
+            "tif" | "tiff" => Some(Self::Tiff),
+            "stp" | "step" => Some(Self::Step),
+            "cdy" => Some(Self::Native),
+            "cdyj" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// Write `doc` to `path` in this format
+    fn write(&self, doc: &Document, path: &Path) -> Result<(), String> {
+        fn text<E: std::fmt::Display>(e: E) -> String {
+            e.to_string()
+        }
+        let raster = || RasterExporter::new(RasterExportSettings::default());
+        match self {
+            Self::Dxf => DxfWriter::new(DxfVersion::R2018).write_file(doc, path).map_err(text),
+            Self::Svg => SvgExporter::default().export(doc, path).map_err(text),
+            Self::Pdf => {
+                PdfExporter::new(PdfExportSettings::for_document(doc)).export(doc, path).map_err(text)
+            }
+            Self::Png => raster().export_png(doc, path).map_err(text),
+            Self::Tiff => raster().export_tiff(doc, path).map_err(text),
+            Self::Step => StepWriter::new(ApplicationProtocol::AP214).write_file(doc, path).map_err(text),
+            Self::Native => NativeFormat::new().save(doc, path).map_err(text),
+            Self::Json => JsonFormat::new().save(doc, path).map_err(text),
+        }
+    }
+}
+
+/// Which entities a rule routes
+///
+/// Patterns use `*` and `?` wildcards and ignore case. Every non-empty list
+/// must match, with any entry in a list enough; an empty selector routes
+/// everything.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExportSelector {
+    /// Layer name patterns
+    #[serde(default)]
+    pub layers: Vec<String>,
+    /// Layers never routed, even if matched above
+    #[serde(default)]
+    pub exclude_layers: Vec<String>,
+    /// Block name patterns; only inserts of matching blocks are routed
+    #[serde(default)]
+    pub blocks: Vec<String>,
+    /// Classification patterns, matched against [`CLASSIFICATION_ATTRIBUTE`]
+    #[serde(default)]
+    pub classifications: Vec<String>,
+    /// Tag patterns, matched against each of the entity's tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Entity type patterns (`Line`, `Hatch`, `PointCloud`, ...)
+    #[serde(default)]
+    pub kinds: Vec<String>,
+}
+
+impl ExportSelector {
+    /// Select every entity
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Select entities on layers matching any of `patterns`
+    pub fn layers(patterns: &[&str]) -> Self {
+        Self {
+            layers: patterns.iter().map(|p| p.to_string()).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Leave out layers matching `pattern`
+    pub fn excluding_layer(mut self, pattern: &str) -> Self {
+        self.exclude_layers.push(pattern.to_string());
+        self
+    }
+
+    /// Also require an insert of a block matching `pattern`
+    pub fn with_block(mut self, pattern: &str) -> Self {
+        self.blocks.push(pattern.to_string());
+        self
+    }
+
+    /// Also require a classification matching `pattern`
+    pub fn with_classification(mut self, pattern: &str) -> Self {
+        self.classifications.push(pattern.to_string());
+        self
+    }
+
+    /// Also require a tag matching `pattern`
+    pub fn with_tag(mut self, pattern: &str) -> Self {
+        self.tags.push(pattern.to_string());
+        self
+    }
+
+    /// Also require an entity type matching `pattern`
+    pub fn with_kind(mut self, pattern: &str) -> Self {
+        self.kinds.push(pattern.to_string());
+        self
+    }
+
+    /// Whether `entity` is routed
+    pub fn matches(&self, entity: &Entity) -> bool {
+        let any = |patterns: &[String], name: Option<&str>| {
+            patterns.is_empty() || name.is_some_and(|name| patterns.iter().any(|p| matches_pattern(p, name)))
+        };
+        let block = match &entity.geometry {
+            GeometryType::Insert(insert) => Some(insert.block_name.as_str()),
+            _ => None,
+        };
+        let classification = entity.attributes.get(CLASSIFICATION_ATTRIBUTE).map(String::as_str);
+        let tags = tags::tags(entity);
+        let tagged = self.tags.is_empty()
+            || tags.iter().any(|t| self.tags.iter().any(|p| matches_pattern(p, t)));
+
+        any(&self.layers, Some(&entity.layer))
+            && !self.exclude_layers.iter().any(|p| matches_pattern(p, &entity.layer))
+            && any(&self.blocks, block)
+            && any(&self.classifications, classification)
+            && tagged
+            && any(&self.kinds, Some(entity.geometry.type_name()))
+    }
+}
+
+/// One output of a pipeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportRule {
+    /// Rule name, unique within the pipeline
+    pub name: String,
+    /// Entities routed to this output
+    #[serde(default)]
+    pub select: ExportSelector,
+    /// Output path, relative to the output directory; `{name}` expands to
+    /// the drawing title and `{rule}` to the rule name
+    pub output: String,
+    /// Output format; taken from the output extension when absent
+    #[serde(default)]
+    pub format: Option<PipelineFormat>,
+    /// Content withheld from this output
+    #[serde(default)]
+    pub redaction: Option<RedactionProfile>,
+    /// Drop layers and blocks the routed entities don't use
+    #[serde(default = "default_purge")]
+    pub purge: bool,
+}
+
+fn default_purge() -> bool {
+    true
+}
+
+impl ExportRule {
+    /// Route entities picked by `select` to `output`
+    pub fn new(name: &str, select: ExportSelector, output: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            select,
+            output: output.to_string(),
+            format: None,
+            redaction: None,
+            purge: true,
+        }
+    }
+
+    /// Write in `format` regardless of the output extension
+    pub fn with_format(mut self, format: PipelineFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Withhold content by `profile`
+    pub fn with_redaction(mut self, profile: RedactionProfile) -> Self {
+        self.redaction = Some(profile);
+        self
+    }
+
+    /// Keep every layer and block definition in the output
+    pub fn keep_definitions(mut self) -> Self {
+        self.purge = false;
+        self
+    }
+
+    /// The format written
+    pub fn resolved_format(&self) -> PipelineResult<PipelineFormat> {
+        self.format
+            .or_else(|| {
+                Path::new(&self.output)
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .and_then(PipelineFormat::from_extension)
+            })
+            .ok_or_else(|| {
+                PipelineError::InvalidRule(format!("{}: no format for output '{}'", self.name, self.output))
+            })
+    }
+
+    /// Output path for a drawing titled `title`
+    pub fn output_path(&self, output_dir: &Path, title: &str) -> PathBuf {
+        output_dir.join(self.output.replace("{name}", title).replace("{rule}", &self.name))
+    }
+
+    /// The part of `doc` this rule writes
+    pub fn extract(&self, doc: &Document) -> Document {
+        let mut subset = doc.without_entities();
+        for entity in doc.entities.iter().filter(|e| self.select.matches(e)) {
+            subset.add_entity(entity.clone());
+        }
+        if let Some(profile) = &self.redaction {
+            subset = profile.redact(&subset).shared;
+        }
+        if self.purge {
+            purge_definitions(&mut subset);
+        }
+        subset
+    }
+}
+
+/// Remove blocks no routed insert reaches and layers nothing uses
+fn purge_definitions(doc: &mut Document) {
+    let inserted = |entities: &[Entity]| -> Vec<String> {
+        entities
+            .iter()
+            .filter_map(|e| match &e.geometry {
+                GeometryType::Insert(insert) => Some(insert.block_name.clone()),
+                _ => None,
+            })
+            .collect()
+    };
+
+    let mut blocks = HashSet::new();
+    let mut pending = inserted(&doc.entities);
+    while let Some(name) = pending.pop() {
+        if blocks.insert(name.clone()) {
+            if let Some(block) = doc.blocks.get(&name) {
+                pending.extend(inserted(&block.entities));
+            }
+        }
+    }
+    doc.blocks.retain(|name, _| blocks.contains(name));
+
+    let used: HashSet<&str> = doc
+        .entities
+        .iter()
+        .chain(doc.blocks.values().flat_map(|b| b.entities.iter()))
+        .map(|e| e.layer.as_str())
+        .chain(["0"])
+        .collect();
+    let unused: Vec<String> = doc.layers.keys().filter(|l| !used.contains(l.as_str())).cloned().collect();
+    for layer in unused {
+        doc.layers.remove(&layer);
+    }
+}
+
+/// What happened to one output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputStatus {
+    /// Written to the output path
+    Written,
+    /// Nothing was routed to the output, so it was not written
+    Empty,
+    /// The exporter failed
+    Failed(String),
+}
+
+/// Outcome of one rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleOutcome {
+    /// Rule name
+    pub rule: String,
+    /// Output path
+    pub path: PathBuf,
+    /// Format written
+    pub format: PipelineFormat,
+    /// Entities routed to the output
+    pub entities: usize,
+    /// Result of writing it
+    pub status: OutputStatus,
+}
+
+/// Results of running a pipeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineReport {
+    /// One outcome per rule, in rule order
+    pub outputs: Vec<RuleOutcome>,
+    /// Entities no rule routed
+    pub unrouted: usize,
+}
+
+impl PipelineReport {
+    /// Whether no output failed
+    pub fn is_success(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Outputs that failed
+    pub fn failures(&self) -> impl Iterator<Item = &RuleOutcome> {
+        self.outputs.iter().filter(|o| matches!(o.status, OutputStatus::Failed(_)))
+    }
+}
+
+/// Reusable set of export rules
+///
+/// ```no_run
+/// use caddy::io::document::Document;
+/// use caddy::io::pipeline::{ExportPipeline, ExportRule, ExportSelector};
+///
+/// let pipeline = ExportPipeline::new("Issue")
+///     .with_rule(ExportRule::new("Structure", ExportSelector::layers(&["S-*"]), "{name}-structure.dxf"))
+///     .with_rule(ExportRule::new("Presentation", ExportSelector::layers(&["A-*"]), "{name}.pdf"));
+/// pipeline.save("issue.pipeline.json").unwrap();
+///
+/// let doc = Document::new();
+/// let report = ExportPipeline::load("issue.pipeline.json").unwrap().run(&doc, "out").unwrap();
+/// assert!(report.is_success());
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportPipeline {
+    /// Pipeline name
+    pub name: String,
+    /// Outputs, written in order
+    #[serde(default)]
+    pub rules: Vec<ExportRule>,
+}
+
+impl ExportPipeline {
+    /// Create an empty pipeline
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            rules: Vec::new(),
+        }
+    }
+
+    /// Add a rule
+    pub fn with_rule(mut self, rule: ExportRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Load a pipeline from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> PipelineResult<Self> {
+        let json = fs::read_to_string(path)?;
+        let pipeline: Self = serde_json::from_str(&json)?;
+        pipeline.validate()?;
+        Ok(pipeline)
+    }
+
+    /// Save the pipeline as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> PipelineResult<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Check rule names are unique and every rule has a format
+    pub fn validate(&self) -> PipelineResult<()> {
+        let mut names = HashSet::new();
+        for rule in &self.rules {
+            if rule.name.trim().is_empty() {
+                return Err(PipelineError::InvalidRule("rule without a name".to_string()));
+            }
+            if !names.insert(rule.name.as_str()) {
+                return Err(PipelineError::InvalidRule(format!("duplicate rule '{}'", rule.name)));
+            }
+            rule.resolved_format()?;
+        }
+        Ok(())
+    }
+
+    /// Write every output for `doc` under `output_dir`
+    ///
+    /// Fails only if the pipeline is invalid or two rules would write the
+    /// same file; export failures are reported per output.
+    pub fn run(&self, doc: &Document, output_dir: impl AsRef<Path>) -> PipelineResult<PipelineReport> {
+        self.validate()?;
+        let title = if doc.metadata.title.trim().is_empty() {
+            "drawing"
+        } else {
+            doc.metadata.title.trim()
+        };
+        let output_dir = output_dir.as_ref();
+
+        let mut paths = HashSet::new();
+        for rule in &self.rules {
+            let path = rule.output_path(output_dir, title);
+            if !paths.insert(path.clone()) {
+                return Err(PipelineError::InvalidRule(format!(
+                    "{}: output {} is written by another rule",
+                    rule.name,
+                    path.display()
+                )));
+            }
+        }
+
+        let mut outputs = Vec::new();
+        for rule in &self.rules {
+            let format = rule.resolved_format()?;
+            let path = rule.output_path(output_dir, title);
+            let subset = rule.extract(doc);
+            let status = if subset.entities.is_empty() {
+                OutputStatus::Empty
+            } else {
+                let created = match path.parent() {
+                    Some(parent) => fs::create_dir_all(parent).map_err(|e| e.to_string()),
+                    None => Ok(()),
+                };
+                match created.and_then(|_| format.write(&subset, &path)) {
+                    Ok(()) => OutputStatus::Written,
+                    Err(error) => OutputStatus::Failed(error),
+                }
+            };
+            outputs.push(RuleOutcome {
+                rule: rule.name.clone(),
+                path,
+                format,
+                entities: subset.entities.len(),
+                status,
+            });
+        }
+
+        let unrouted = doc
+            .entities
+            .iter()
+            .filter(|e| !self.rules.iter().any(|r| r.select.matches(e)))
+            .count();
+        Ok(PipelineReport { outputs, unrouted })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{Block, Insert, Layer, Line, Vec3};
+    use std::collections::HashMap;
+
+    fn line_on(layer: &str) -> Entity {
+        Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::new(0.0, 0.0, 0.0),
+                end: Vec3::new(10.0, 5.0, 0.0),
+            }),
+            layer.to_string(),
+        )
+    }
+
+    fn insert(block: &str, layer: &str) -> Entity {
+        Entity::new(
+            GeometryType::Insert(Insert {
+                block_name: block.to_string(),
+                position: Vec3::new(5.0, 5.0, 0.0),
+                scale: Vec3::new(1.0, 1.0, 1.0),
+                rotation: 0.0,
+                attributes: HashMap::new(),
+            }),
+            layer.to_string(),
+        )
+    }
+
+    fn drawing() -> Document {
+        let mut doc = Document::new();
+        doc.metadata.title = "Tower".to_string();
+        for name in ["S-COLS", "S-BEAM", "A-ANNO", "A-DOOR"] {
+            let mut layer = doc.get_layer("0").unwrap().clone();
+            layer.name = name.to_string();
+            doc.add_layer(layer);
+        }
+        doc.add_block(Block {
+            name: "DOOR".to_string(),
+            base_point: Vec3::new(0.0, 0.0, 0.0),
+            entities: vec![line_on("A-DOOR")],
+            description: String::new(),
+        });
+        doc.add_entity(line_on("S-COLS"));
+        let mut beam = line_on("S-BEAM");
+        tags::add_tag(&mut beam, "phase-2").unwrap();
+        doc.add_entity(beam);
+        let mut classified = line_on("0");
+        classified.attributes.insert(CLASSIFICATION_ATTRIBUTE.to_string(), "Structural".to_string());
+        doc.add_entity(classified);
+        doc.add_entity(line_on("A-ANNO"));
+        doc.add_entity(insert("DOOR", "A-ANNO"));
+        doc.add_entity(line_on("E-POWR"));
+        doc
+    }
+
+    #[test]
+    fn test_selectors() {
+        let doc = drawing();
+        let count = |select: ExportSelector| doc.entities.iter().filter(|e| select.matches(e)).count();
+
+        assert_eq!(count(ExportSelector::all()), 6);
+        assert_eq!(count(ExportSelector::layers(&["s-*"])), 2);
+        assert_eq!(count(ExportSelector::layers(&["S-*"]).excluding_layer("S-BEAM")), 1);
+        assert_eq!(count(ExportSelector::all().with_classification("struct*")), 1);
+        assert_eq!(count(ExportSelector::all().with_block("DOOR")), 1);
+        assert_eq!(count(ExportSelector::all().with_tag("PHASE-*")), 1);
+        assert_eq!(count(ExportSelector::layers(&["A-*"]).with_kind("line")), 1);
+    }
+
+    #[test]
+    fn test_pipeline_routes_outputs() {
+        let doc = drawing();
+        let dir = std::env::temp_dir().join(format!("pipeline-{}", doc.id));
+        let pipeline = ExportPipeline::new("Issue")
+            .with_rule(ExportRule::new(
+                "Structure",
+                ExportSelector::layers(&["S-*"]),
+                "{name}-{rule}.cdy",
+            ))
+            .with_rule(ExportRule::new("Presentation", ExportSelector::layers(&["A-*"]), "sheets/{name}.svg"))
+            .with_rule(
+                ExportRule::new("Doors", ExportSelector::all().with_block("DOOR"), "{name}-doors.out")
+                    .with_format(PipelineFormat::Json),
+            )
+            .with_rule(ExportRule::new("Print", ExportSelector::layers(&["A-ANNO"]), "{name}.pdf"))
+            .with_rule(ExportRule::new("Mech", ExportSelector::layers(&["M-*"]), "{name}-mech.dxf"));
+
+        // Pipelines round-trip through their file
+        let file = std::env::temp_dir().join(format!("pipeline-{}.json", doc.id));
+        pipeline.save(&file).unwrap();
+        assert_eq!(ExportPipeline::load(&file).unwrap(), pipeline);
+        fs::remove_file(&file).ok();
+
+        let report = pipeline.run(&doc, &dir).unwrap();
+        let status: Vec<&OutputStatus> = report.outputs.iter().map(|o| &o.status).collect();
+        assert_eq!(status[..3], [&OutputStatus::Written; 3]);
+        assert!(matches!(status[3], OutputStatus::Failed(_)));
+        assert_eq!(status[4], &OutputStatus::Empty);
+        assert!(!report.is_success());
+        assert_eq!(report.failures().count(), 1);
+        // The classified line on layer 0 and the electrical line
+        assert_eq!(report.unrouted, 2);
+
+        // Outputs hold their subset, with unused definitions purged
+        let structure = NativeFormat::new().load(dir.join("Tower-Structure.cdy")).unwrap();
+        assert_eq!(structure.entities.len(), 2);
+        assert!(structure.blocks.is_empty());
+        let mut layers: Vec<&String> = structure.layers.keys().collect();
+        layers.sort();
+        assert_eq!(layers, ["0", "S-BEAM", "S-COLS"]);
+
+        let doors = JsonFormat::new().load(dir.join("Tower-doors.out")).unwrap();
+        assert_eq!(doors.entities.len(), 1);
+        assert!(doors.blocks.contains_key("DOOR"));
+        assert!(doors.layers.contains_key("A-DOOR"));
+        assert!(dir.join("sheets/Tower.svg").exists());
+        fs::remove_dir_all(&dir).ok();
+
+        // Two rules may not write the same file
+        let clash = pipeline.with_rule(ExportRule::new("Again", ExportSelector::all(), "{name}.pdf"));
+        assert!(matches!(clash.run(&doc, &dir), Err(PipelineError::InvalidRule(_))));
+        let unknown =
+            ExportPipeline::new("Bad").with_rule(ExportRule::new("X", ExportSelector::all(), "out.xyz"));
+        assert!(matches!(unknown.validate(), Err(PipelineError::InvalidRule(_))));
+    }
+}
@@ -0,0 +1,384 @@
+// CADDY - Enterprise CAD System
+// File I/O System - Point Cloud Import
+
+//! # Point Cloud Import
+//!
+//! Reads laser scan and survey point clouds into a [`PointCloud`]:
+//!
+//! - **XYZ** (`.xyz`, `.txt`, `.csv`): one point per line, `x y z`, with
+//!   optional intensity and `r g b` columns; space, tab, comma or semicolon
+//!   separated
+//! - **PTS** (`.pts`): Leica scans, a point count line before each scan and
+//!   `x y z intensity [r g b]` per point, intensity from -2048 to 2047
+//! - **LAS** (`.las`): ASPRS LAS 1.0 to 1.4, point formats 0 to 10;
+//!   compressed LAZ files are not supported
+//!
+//! [`PointCloudImportOptions`] thins the cloud as it is imported, which is
+//! usually wanted for scans of tens of millions of points.
+
+use crate::geometry::pointcloud::PointCloud;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use thiserror::Error;
+
+/// Point cloud import errors
+#[derive(Error, Debug)]
+pub enum PointCloudError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Parse error at line {line}: {message}")]
+    Parse { line: usize, message: String },
+
+    #[error("Invalid LAS file: {0}")]
+    InvalidLas(String),
+
+    #[error("Unsupported format: {0}")]
+    UnsupportedFormat(String),
+}
+
+pub type PointCloudResult<T> = Result<T, PointCloudError>;
+
+/// Point cloud file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointCloudFormat {
+    Xyz,
+    Pts,
+    Las,
+}
+
+impl PointCloudFormat {
+    /// Format from a file extension
+    pub fn from_path(path: &Path) -> PointCloudResult<Self> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        match extension.as_str() {
+            "xyz" | "txt" | "csv" => Ok(Self::Xyz),
+            "pts" => Ok(Self::Pts),
+            "las" => Ok(Self::Las),
+            "laz" => Err(PointCloudError::UnsupportedFormat(
+                "compressed LAZ; decompress to LAS first".to_string(),
+            )),
+            other => Err(PointCloudError::UnsupportedFormat(other.to_string())),
+        }
+    }
+}
+
+/// Thinning applied while importing
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PointCloudImportOptions {
+    /// Keep one averaged point per voxel of this edge length
+    pub voxel_size: Option<f64>,
+    /// Keep every nth point beyond this many, after voxel thinning
+    pub max_points: Option<usize>,
+}
+
+impl PointCloudImportOptions {
+    /// Thin `cloud` as configured
+    pub fn apply(&self, cloud: PointCloud) -> PointCloud {
+        let cloud = match self.voxel_size {
+            Some(size) => cloud.voxel_downsample(size),
+            None => cloud,
+        };
+        match self.max_points {
+            Some(max) if max > 0 && cloud.len() > max => cloud.subsample(cloud.len().div_ceil(max)),
+            _ => cloud,
+        }
+    }
+}
+
+/// Import a point cloud file, choosing the reader by extension
+pub fn import_point_cloud(
+    path: impl AsRef<Path>,
+    options: &PointCloudImportOptions,
+) -> PointCloudResult<PointCloud> {
+    let path = path.as_ref();
+    let format = PointCloudFormat::from_path(path)?;
+    let reader = BufReader::new(File::open(path)?);
+    let cloud = match format {
+        PointCloudFormat::Xyz => read_xyz(reader)?,
+        PointCloudFormat::Pts => read_pts(reader)?,
+        PointCloudFormat::Las => read_las(reader)?,
+    };
+    Ok(options.apply(cloud))
+}
+
+/// Read XYZ text: `x y z [intensity] [r g b]`
+pub fn read_xyz<R: BufRead>(reader: R) -> PointCloudResult<PointCloud> {
+    read_text(reader, false)
+}
+
+/// Read Leica PTS text: count lines and `x y z intensity [r g b]`
+pub fn read_pts<R: BufRead>(reader: R) -> PointCloudResult<PointCloud> {
+    read_text(reader, true)
+}
+
+fn read_text<R: BufRead>(reader: R, pts: bool) -> PointCloudResult<PointCloud> {
+    let mut cloud: Option<PointCloud> = None;
+    for (index, text) in reader.lines().enumerate() {
+        let text = text?;
+        let line = index + 1;
+        let content = text.trim();
+        if content.is_empty() || content.starts_with('#') || content.starts_with("//") {
+            continue;
+        }
+
+        let values = content
+            .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+            .filter(|v| !v.is_empty())
+            .map(str::parse::<f64>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PointCloudError::Parse {
+                line,
+                message: e.to_string(),
+            })?;
+        // PTS scans each start with their point count
+        if pts && values.len() == 1 {
+            continue;
+        }
+
+        let (intensity, color) = match values.len() {
+            3 => (None, None),
+            4 => (Some(values[3]), None),
+            6 => (None, Some(&values[3..6])),
+            7 => (Some(values[3]), Some(&values[4..7])),
+            n => {
+                return Err(PointCloudError::Parse {
+                    line,
+                    message: format!("expected 3, 4, 6 or 7 values, found {}", n),
+                })
+            }
+        };
+        let intensity = intensity.map(|i| {
+            if pts {
+                ((i + 2048.0).clamp(0.0, 4095.0) * 16.0) as u16
+            } else {
+                i.round().clamp(0.0, u16::MAX as f64) as u16
+            }
+        });
+        let color = color.map(|c| [c[0], c[1], c[2]].map(|v| v.round().clamp(0.0, 255.0) as u8));
+
+        let position = [values[0], values[1], values[2]];
+        cloud
+            .get_or_insert_with(|| PointCloud::new(position.map(f64::floor)))
+            .push(position, color, intensity);
+    }
+    Ok(cloud.unwrap_or_default())
+}
+
+/// Shortest point record for each LAS point format
+const LAS_RECORD_LENGTHS: [usize; 11] = [20, 28, 26, 34, 57, 63, 30, 36, 38, 59, 67];
+
+/// Read an uncompressed LAS file
+pub fn read_las<R: Read>(mut reader: R) -> PointCloudResult<PointCloud> {
+    let mut header = vec![0u8; 227];
+    reader.read_exact(&mut header)?;
+    if &header[0..4] != b"LASF" {
+        return Err(PointCloudError::InvalidLas("missing LASF signature".to_string()));
+    }
+
+    let (major, minor) = (header[24], header[25]);
+    let header_size = u16_at(&header, 94) as usize;
+    let point_offset = u32_at(&header, 96) as usize;
+    if major != 1 || header_size < 227 || point_offset < header_size {
+        return Err(PointCloudError::InvalidLas(format!(
+            "version {}.{} with a {} byte header",
+            major, minor, header_size
+        )));
+    }
+    header.resize(header_size, 0);
+    reader.read_exact(&mut header[227..])?;
+    // Variable length records are not needed for the points themselves
+    io::copy(
+        &mut (&mut reader).take((point_offset - header_size) as u64),
+        &mut io::sink(),
+    )?;
+
+    let format = header[104];
+    if format & 0xC0 != 0 {
+        return Err(PointCloudError::UnsupportedFormat(
+            "compressed LAZ; decompress to LAS first".to_string(),
+        ));
+    }
+    let format = format as usize;
+    let Some(&min_length) = LAS_RECORD_LENGTHS.get(format) else {
+        return Err(PointCloudError::InvalidLas(format!("point format {}", format)));
+    };
+    let record_length = u16_at(&header, 105) as usize;
+    if record_length < min_length {
+        return Err(PointCloudError::InvalidLas(format!(
+            "{} byte records for point format {}",
+            record_length, format
+        )));
+    }
+
+    let legacy_count = u32_at(&header, 107) as u64;
+    let count = if minor >= 4 && header_size >= 255 && legacy_count == 0 {
+        u64_at(&header, 247)
+    } else {
+        legacy_count
+    };
+    let scale = [f64_at(&header, 131), f64_at(&header, 139), f64_at(&header, 147)];
+    let offset = [f64_at(&header, 155), f64_at(&header, 163), f64_at(&header, 171)];
+    let color_at = match format {
+        2 => Some(20),
+        3 | 5 => Some(28),
+        7 | 8 | 10 => Some(30),
+        _ => None,
+    };
+
+    let mut cloud: Option<PointCloud> = None;
+    let mut colors: Vec<[u16; 3]> = Vec::new();
+    let mut record = vec![0u8; record_length];
+    for read in 0..count {
+        reader.read_exact(&mut record).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => PointCloudError::InvalidLas(format!(
+                "file ends after {} of {} points",
+                read, count
+            )),
+            _ => PointCloudError::Io(e),
+        })?;
+        let position = [0, 1, 2].map(|axis| {
+            i32::from_le_bytes(record[axis * 4..axis * 4 + 4].try_into().unwrap()) as f64
+                * scale[axis]
+                + offset[axis]
+        });
+        if let Some(at) = color_at {
+            colors.push([0, 1, 2].map(|channel| u16_at(&record, at + channel * 2)));
+        }
+        cloud
+            .get_or_insert_with(|| {
+                let mut cloud = PointCloud::new(position.map(f64::floor));
+                cloud.positions.reserve(count.min(1 << 26) as usize);
+                cloud
+            })
+            .push(position, None, Some(u16_at(&record, 12)));
+    }
+
+    let mut cloud = cloud.unwrap_or_default();
+    // Colors are meant to be 16-bit, but many writers store 8-bit values
+    let eight_bit = colors.iter().flatten().all(|&c| c <= 255);
+    cloud.colors = colors
+        .into_iter()
+        .map(|c| c.map(|v| if eight_bit { v as u8 } else { (v >> 8) as u8 }))
+        .collect();
+    Ok(cloud)
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn u64_at(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+fn f64_at(bytes: &[u8], at: usize) -> f64 {
+    f64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_xyz_and_pts() {
+        let xyz = "\
+# x y z r g b
+1000.5, 2000.25, 10, 255, 0, 0
+1001.5;2000.25;11;0;128;0
+";
+        let cloud = read_xyz(xyz.as_bytes()).unwrap();
+        assert_eq!(cloud.len(), 2);
+        assert_eq!(cloud.origin, [1000.0, 2000.0, 10.0]);
+        assert_eq!(cloud.colors, vec![[255, 0, 0], [0, 128, 0]]);
+        assert!(!cloud.has_intensities());
+        assert!((cloud.point(1).x - 1001.5).abs() < 1e-9);
+
+        let pts = "2\n0 0 0 -2048\n1 1 1 2047 10 20 30\n1\n2 2 2 0\n";
+        let cloud = read_pts(pts.as_bytes()).unwrap();
+        assert_eq!(cloud.len(), 3);
+        assert_eq!(cloud.intensities, vec![0, 65520, 32768]);
+        assert_eq!(cloud.colors, vec![[255, 255, 255], [10, 20, 30], [255, 255, 255]]);
+
+        let err = read_xyz("1 2\n".as_bytes()).unwrap_err();
+        assert!(matches!(err, PointCloudError::Parse { line: 1, .. }));
+
+        let options = PointCloudImportOptions {
+            voxel_size: None,
+            max_points: Some(2),
+        };
+        assert_eq!(options.apply(cloud).len(), 2);
+    }
+
+    /// LAS 1.2, point format 2, holding `points` as (x, y, z, intensity, rgb)
+    fn las(points: &[([f64; 3], u16, [u16; 3])]) -> Vec<u8> {
+        let mut bytes = vec![0u8; 227];
+        bytes[0..4].copy_from_slice(b"LASF");
+        bytes[24] = 1;
+        bytes[25] = 2;
+        bytes[94..96].copy_from_slice(&227u16.to_le_bytes());
+        bytes[96..100].copy_from_slice(&227u32.to_le_bytes());
+        bytes[104] = 2;
+        bytes[105..107].copy_from_slice(&26u16.to_le_bytes());
+        bytes[107..111].copy_from_slice(&(points.len() as u32).to_le_bytes());
+        for axis in 0..3 {
+            let at = 131 + axis * 8;
+            bytes[at..at + 8].copy_from_slice(&0.001f64.to_le_bytes());
+        }
+        // Coordinate offsets keep the scaled integers in range
+        let offsets: [f64; 3] = [500_000.0, 4_200_000.0, 0.0];
+        for (axis, offset) in offsets.iter().enumerate() {
+            let at = 155 + axis * 8;
+            bytes[at..at + 8].copy_from_slice(&offset.to_le_bytes());
+        }
+        for (position, intensity, color) in points {
+            let mut record = vec![0u8; 26];
+            for (axis, offset) in offsets.iter().enumerate() {
+                let value = ((position[axis] - offset) / 0.001).round() as i32;
+                record[axis * 4..axis * 4 + 4].copy_from_slice(&value.to_le_bytes());
+            }
+            record[12..14].copy_from_slice(&intensity.to_le_bytes());
+            for (channel, value) in color.iter().enumerate() {
+                let at = 20 + channel * 2;
+                record[at..at + 2].copy_from_slice(&value.to_le_bytes());
+            }
+            bytes.extend(record);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_read_las() {
+        let bytes = las(&[
+            ([500_010.125, 4_200_000.5, 12.25], 300, [65535, 0, 32768]),
+            ([500_011.0, 4_200_001.0, 13.0], 400, [0, 65535, 0]),
+        ]);
+        let cloud = read_las(bytes.as_slice()).unwrap();
+        assert_eq!(cloud.len(), 2);
+        assert_eq!(cloud.origin, [500_010.0, 4_200_000.0, 12.0]);
+        let p = cloud.point(0);
+        assert!((p.x - 500_010.125).abs() < 1e-6);
+        assert!((p.y - 4_200_000.5).abs() < 1e-6);
+        assert_eq!(cloud.intensities, vec![300, 400]);
+        assert_eq!(cloud.colors, vec![[255, 0, 128], [0, 255, 0]]);
+
+        // Truncated point data
+        let err = read_las(&bytes[..bytes.len() - 4]).unwrap_err();
+        assert!(matches!(err, PointCloudError::InvalidLas(_)));
+        let mut compressed = bytes.clone();
+        compressed[104] |= 0x80;
+        assert!(matches!(
+            read_las(compressed.as_slice()),
+            Err(PointCloudError::UnsupportedFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,656 @@
+// CADDY - Enterprise CAD System
+// File I/O System - Progressive Loading Module
+// Agent 6 - File I/O System Developer
+
+//! Progressive loading of large native files.
+//!
+//! [`TiledFormat`] writes a native file whose entities are grouped into
+//! spatial tiles behind a directory. [`ProgressiveLoader`] reads the
+//! document skeleton (layers, blocks, views, settings) and the directory,
+//! then streams tiles on a background thread: those intersecting the
+//! initial view first, the rest nearest-first. The drawing can be inspected
+//! as soon as the visible tiles are in, while everything else fills in.
+//!
+//! Older native files load whole and are then delivered in the same
+//! order, so callers don't need a separate path for them.
+
+use crate::io::document::{BoundingBox, Document, Entity, Vec3, View};
+use crate::io::native::{
+    FileMetadata, NativeError, NativeFileContainer, NativeFormat, NativeResult, MAGIC_BYTES,
+    TILED_VERSION,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use uuid::Uuid;
+
+/// Default number of entities per tile
+pub const DEFAULT_TILE_SIZE: usize = 2048;
+
+/// Directory entry for one tile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TileEntry {
+    /// Union of the tile's entity bounds; `None` for entities without
+    /// finite bounds
+    bounds: Option<BoundingBox>,
+    /// Number of entities in the tile
+    entities: u64,
+    /// Offset of the tile's data from the start of the tile section
+    offset: u64,
+    /// Length of the tile's data
+    length: u64,
+}
+
+/// Entities of one tile, each with its position in the document
+type Chunk = Vec<(u64, Entity)>;
+
+// ==================== WRITING ====================
+
+/// Native binary format with entities stored in spatial tiles
+///
+/// Files are ordinary `.cdy` files at format version 3, readable by
+/// [`NativeFormat`] as well as [`ProgressiveLoader`]. Tiles are neither
+/// compressed nor encrypted; password-protected drawings should be saved
+/// with [`NativeFormat`].
+pub struct TiledFormat {
+    /// Maximum number of entities per tile
+    tile_size: usize,
+}
+
+impl TiledFormat {
+    /// Create a tiled format handler with the default tile size
+    pub fn new() -> Self {
+        Self { tile_size: DEFAULT_TILE_SIZE }
+    }
+
+    /// Set the maximum number of entities per tile
+    pub fn with_tile_size(mut self, tile_size: usize) -> Self {
+        self.tile_size = tile_size.max(1);
+        self
+    }
+
+    /// Save document in tiled native format
+    pub fn save<P: AsRef<Path>>(&self, doc: &Document, path: P) -> NativeResult<()> {
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+        self.write_to(doc, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write document in tiled native format
+    pub fn write_to<W: Write>(&self, doc: &Document, writer: &mut W) -> NativeResult<()> {
+        // Everything but the entities, read before any tile
+        let skeleton = serialize(&NativeFileContainer {
+            version: TILED_VERSION,
+            document: doc.without_entities(),
+            metadata: FileMetadata::new(),
+        })?;
+
+        let mut directory = Vec::new();
+        let mut tiles = Vec::new();
+        let mut offset = 0u64;
+        for (bounds, members) in partition(doc.entities.iter().map(Entity::bounding_box), self.tile_size) {
+            let chunk: Vec<(u64, &Entity)> =
+                members.iter().map(|&i| (i as u64, &doc.entities[i])).collect();
+            let data = serialize(&chunk)?;
+            directory.push(TileEntry {
+                bounds,
+                entities: members.len() as u64,
+                offset,
+                length: data.len() as u64,
+            });
+            offset += data.len() as u64;
+            tiles.push(data);
+        }
+        let directory = serialize(&directory)?;
+
+        // Magic bytes, version, no compression, no encryption
+        writer.write_all(MAGIC_BYTES)?;
+        writer.write_all(&TILED_VERSION.to_le_bytes())?;
+        writer.write_all(&[0, 0])?;
+        for section in [&skeleton, &directory] {
+            writer.write_all(&(section.len() as u64).to_le_bytes())?;
+            writer.write_all(section)?;
+        }
+        for tile in &tiles {
+            writer.write_all(tile)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for TiledFormat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn serialize<T: Serialize + ?Sized>(value: &T) -> NativeResult<Vec<u8>> {
+    bincode::serialize(value).map_err(|e| NativeError::Serialization(e.to_string()))
+}
+
+/// Group entities, given by their bounds, into tiles of at most
+/// `tile_size`, splitting the longer side of each group at the median entity
+/// center. Entities without finite bounds share a final unbounded tile.
+fn partition(
+    bounds: impl Iterator<Item = BoundingBox>,
+    tile_size: usize,
+) -> Vec<(Option<BoundingBox>, Vec<usize>)> {
+    let mut bounded = Vec::new();
+    let mut unbounded = Vec::new();
+    for (i, bounds) in bounds.enumerate() {
+        let finite = [bounds.min, bounds.max]
+            .iter()
+            .all(|v| v.x.is_finite() && v.y.is_finite() && v.z.is_finite());
+        if finite {
+            bounded.push((i, bounds));
+        } else {
+            unbounded.push(i);
+        }
+    }
+
+    let mut tiles = Vec::new();
+    split(&mut bounded, tile_size.max(1), &mut tiles);
+    if !unbounded.is_empty() {
+        tiles.push((None, unbounded));
+    }
+    tiles
+}
+
+fn split(
+    items: &mut [(usize, BoundingBox)],
+    tile_size: usize,
+    tiles: &mut Vec<(Option<BoundingBox>, Vec<usize>)>,
+) {
+    if items.is_empty() {
+        return;
+    }
+    if items.len() <= tile_size {
+        let bounds = items
+            .iter()
+            .fold(BoundingBox::invalid(), |acc, (_, b)| acc.union(b));
+        let mut members: Vec<usize> = items.iter().map(|(i, _)| *i).collect();
+        members.sort_unstable();
+        tiles.push((Some(bounds), members));
+        return;
+    }
+
+    let centers = items
+        .iter()
+        .fold(BoundingBox::invalid(), |acc, (_, b)| acc.union(&BoundingBox::from_point(b.center())));
+    let size = centers.size();
+    let axis = |b: &BoundingBox| {
+        let c = b.center();
+        if size.x >= size.y { c.x } else { c.y }
+    };
+    let mid = items.len() / 2;
+    items.select_nth_unstable_by(mid, |a, b| axis(&a.1).total_cmp(&axis(&b.1)));
+    let (low, high) = items.split_at_mut(mid);
+    split(low, tile_size, tiles);
+    split(high, tile_size, tiles);
+}
+
+// ==================== READING ====================
+
+/// Read the rest of a tiled file after its 10-byte header, in full
+///
+/// Used by [`NativeFormat`] for version 3 files; tiles are laid out in
+/// directory order, so no seeking is needed.
+pub(crate) fn read_tiled<R: Read>(reader: &mut R) -> NativeResult<Document> {
+    let (mut document, directory) = read_sections(reader)?;
+    let mut entities = Vec::new();
+    for tile in &directory {
+        entities.extend(read_chunk(reader, tile)?);
+    }
+    entities.sort_by_key(|(ordinal, _)| *ordinal);
+    document.entities = entities.into_iter().map(|(_, e)| e).collect();
+    document.rebuild_spatial_index();
+    Ok(document)
+}
+
+fn read_section<R: Read>(reader: &mut R) -> NativeResult<Vec<u8>> {
+    let mut length = [0u8; 8];
+    reader.read_exact(&mut length)?;
+    let mut data = vec![0u8; u64::from_le_bytes(length) as usize];
+    reader.read_exact(&mut data)?;
+    Ok(data)
+}
+
+fn deserialize<T: serde::de::DeserializeOwned>(data: &[u8]) -> NativeResult<T> {
+    bincode::deserialize(data).map_err(|e| NativeError::Deserialization(e.to_string()))
+}
+
+/// Skeleton document and tile directory
+fn read_sections<R: Read>(reader: &mut R) -> NativeResult<(Document, Vec<TileEntry>)> {
+    let container: NativeFileContainer = deserialize(&read_section(reader)?)?;
+    let directory: Vec<TileEntry> = deserialize(&read_section(reader)?)?;
+    Ok((container.document, directory))
+}
+
+fn read_chunk<R: Read>(reader: &mut R, tile: &TileEntry) -> NativeResult<Chunk> {
+    let mut data = vec![0u8; tile.length as usize];
+    reader.read_exact(&mut data)?;
+    deserialize(&data)
+}
+
+trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+/// Where tiles are read from
+enum TileSource {
+    /// Tiled file, with the offset of its tile section
+    File { reader: Box<dyn ReadSeek>, start: u64 },
+    /// Older file loaded whole and tiled in memory
+    Memory(Vec<Option<Chunk>>),
+}
+
+impl TileSource {
+    fn read(&mut self, index: usize, tile: &TileEntry) -> NativeResult<Chunk> {
+        match self {
+            TileSource::File { reader, start } => {
+                reader.seek(SeekFrom::Start(*start + tile.offset))?;
+                read_chunk(reader, tile)
+            }
+            TileSource::Memory(chunks) => chunks
+                .get_mut(index)
+                .and_then(Option::take)
+                .ok_or(NativeError::InvalidFormat),
+        }
+    }
+}
+
+/// Region covered by a named view, unbounded in depth
+///
+/// Twist is ignored; the region is the axis-aligned view rectangle.
+pub fn view_bounds(view: &View) -> BoundingBox {
+    let (w, h) = (view.width / 2.0, view.height / 2.0);
+    BoundingBox::new(
+        Vec3::new(view.center.x - w, view.center.y - h, f64::NEG_INFINITY),
+        Vec3::new(view.center.x + w, view.center.y + h, f64::INFINITY),
+    )
+}
+
+/// Plan distance between two boxes; zero when they overlap
+fn gap(a: &BoundingBox, b: &BoundingBox) -> f64 {
+    let dx = (a.min.x - b.max.x).max(b.min.x - a.max.x).max(0.0);
+    let dy = (a.min.y - b.max.y).max(b.min.y - a.max.y).max(0.0);
+    dx.hypot(dy)
+}
+
+/// Opens a native file for progressive loading
+///
+/// ```no_run
+/// use caddy::io::progressive::{view_bounds, ProgressiveLoader};
+///
+/// let loader = ProgressiveLoader::open("site.cdy").unwrap();
+/// let view = loader.skeleton().views.get("Entry").map(view_bounds);
+/// let (mut doc, mut load) = loader.start(view.or(loader.extents()).unwrap());
+/// load.wait_visible(&mut doc).unwrap();
+/// // Draw `doc`, then keep calling `load.poll(&mut doc)` between frames
+/// ```
+pub struct ProgressiveLoader {
+    skeleton: Document,
+    tiles: Vec<TileEntry>,
+    source: TileSource,
+}
+
+impl ProgressiveLoader {
+    /// Open a native file; older files are loaded whole
+    pub fn open<P: AsRef<Path>>(path: P) -> NativeResult<Self> {
+        Self::open_with(path, &NativeFormat::new())
+    }
+
+    /// Open a native file, loading older files with `format` (for example
+    /// one carrying the open password)
+    pub fn open_with<P: AsRef<Path>>(path: P, format: &NativeFormat) -> NativeResult<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?), format)
+    }
+
+    /// Open native data from any seekable reader
+    pub fn from_reader<R>(mut reader: R, format: &NativeFormat) -> NativeResult<Self>
+    where
+        R: Read + Seek + Send + 'static,
+    {
+        let start = reader.stream_position()?;
+        let mut header = [0u8; 10];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC_BYTES {
+            return Err(NativeError::InvalidFormat);
+        }
+        let version = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+
+        if version == TILED_VERSION {
+            let (skeleton, tiles) = read_sections(&mut reader)?;
+            let start = reader.stream_position()?;
+            return Ok(Self {
+                skeleton,
+                tiles,
+                source: TileSource::File { reader: Box::new(reader), start },
+            });
+        }
+
+        reader.seek(SeekFrom::Start(start))?;
+        let mut document = format.read_from(reader)?;
+        let groups = partition(document.entities.iter().map(Entity::bounding_box), DEFAULT_TILE_SIZE);
+        let mut entities: Vec<Option<Entity>> =
+            std::mem::take(&mut document.entities).into_iter().map(Some).collect();
+        let mut tiles = Vec::new();
+        let mut chunks = Vec::new();
+        for (bounds, members) in groups {
+            let chunk: Chunk = members
+                .iter()
+                .filter_map(|&i| Some((i as u64, entities[i].take()?)))
+                .collect();
+            tiles.push(TileEntry { bounds, entities: chunk.len() as u64, offset: 0, length: 0 });
+            chunks.push(Some(chunk));
+        }
+        Ok(Self { skeleton: document, tiles, source: TileSource::Memory(chunks) })
+    }
+
+    /// Whether the file stores tiles, rather than having been loaded whole
+    pub fn is_tiled(&self) -> bool {
+        matches!(self.source, TileSource::File { .. })
+    }
+
+    /// The document without its entities
+    pub fn skeleton(&self) -> &Document {
+        &self.skeleton
+    }
+
+    /// Number of entities in the file
+    pub fn entity_count(&self) -> usize {
+        self.tiles.iter().map(|t| t.entities as usize).sum()
+    }
+
+    /// Number of tiles
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Extents of all bounded entities, known before any are loaded
+    pub fn extents(&self) -> Option<BoundingBox> {
+        self.tiles
+            .iter()
+            .filter_map(|t| t.bounds)
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// Start streaming tiles, those intersecting `view` first
+    ///
+    /// Returns the skeleton document, which fills in as the load is polled.
+    pub fn start(self, view: BoundingBox) -> (Document, ProgressiveLoad) {
+        let (tiles_tx, tiles_rx) = mpsc::channel();
+        let (focus_tx, focus_rx) = mpsc::channel();
+        let bounds: Vec<Option<BoundingBox>> = self.tiles.iter().map(|t| t.bounds).collect();
+        let progress = LoadProgress {
+            loaded_entities: 0,
+            total_entities: self.entity_count(),
+            loaded_tiles: 0,
+            total_tiles: self.tiles.len(),
+        };
+
+        let tiles = self.tiles;
+        let mut source = self.source;
+        thread::spawn(move || {
+            let mut focus = view;
+            let mut pending: Vec<usize> = (0..tiles.len()).collect();
+            while !pending.is_empty() {
+                while let Ok(next) = focus_rx.try_recv() {
+                    focus = next;
+                }
+                let at = (0..pending.len())
+                    .min_by(|&a, &b| {
+                        priority(&tiles[pending[a]], &focus).total_cmp(&priority(&tiles[pending[b]], &focus))
+                    })
+                    .expect("pending tiles");
+                let index = pending.swap_remove(at);
+                let chunk = source.read(index, &tiles[index]);
+                let failed = chunk.is_err();
+                if tiles_tx.send((index, chunk)).is_err() || failed {
+                    return;
+                }
+            }
+        });
+
+        let load = ProgressiveLoad {
+            receiver: tiles_rx,
+            focus: focus_tx,
+            view,
+            loaded: vec![false; bounds.len()],
+            bounds,
+            ordinals: HashMap::new(),
+            progress,
+            finished: false,
+        };
+        (self.skeleton, load)
+    }
+}
+
+/// Loading order of a tile: distance from the view, unbounded tiles last
+fn priority(tile: &TileEntry, view: &BoundingBox) -> f64 {
+    tile.bounds.map_or(f64::INFINITY, |b| gap(&b, view))
+}
+
+/// How far a progressive load has got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadProgress {
+    pub loaded_entities: usize,
+    pub total_entities: usize,
+    pub loaded_tiles: usize,
+    pub total_tiles: usize,
+}
+
+impl LoadProgress {
+    /// Whether every tile has been loaded
+    pub fn is_complete(&self) -> bool {
+        self.loaded_tiles == self.total_tiles
+    }
+
+    /// Fraction of entities loaded, from 0 to 1
+    pub fn fraction(&self) -> f64 {
+        if self.total_entities == 0 {
+            1.0
+        } else {
+            self.loaded_entities as f64 / self.total_entities as f64
+        }
+    }
+}
+
+/// Tiles streaming in from a [`ProgressiveLoader`]
+///
+/// Entities are appended to the document as their tiles arrive, in loading
+/// order. Once the last tile is in they are put back in file order, so draw
+/// order matches a plain load; entities the user added meanwhile stay at the
+/// end. Dropping the load stops the background reader.
+pub struct ProgressiveLoad {
+    receiver: Receiver<(usize, NativeResult<Chunk>)>,
+    focus: Sender<BoundingBox>,
+    view: BoundingBox,
+    bounds: Vec<Option<BoundingBox>>,
+    loaded: Vec<bool>,
+    ordinals: HashMap<Uuid, u64>,
+    progress: LoadProgress,
+    finished: bool,
+}
+
+impl ProgressiveLoad {
+    /// Current progress
+    pub fn progress(&self) -> LoadProgress {
+        self.progress
+    }
+
+    /// Whether every tile intersecting the current view has been loaded
+    pub fn is_visible_complete(&self) -> bool {
+        self.bounds.iter().enumerate().all(|(i, bounds)| {
+            self.loaded[i] || bounds.is_none_or(|b| gap(&b, &self.view) > 0.0)
+        })
+    }
+
+    /// Prioritise tiles near a new view, after the user pans or zooms
+    pub fn refocus(&mut self, view: BoundingBox) {
+        self.view = view;
+        // The reader has finished if this fails
+        let _ = self.focus.send(view);
+    }
+
+    /// Add the tiles that have arrived to `doc`, without blocking
+    pub fn poll(&mut self, doc: &mut Document) -> NativeResult<LoadProgress> {
+        while !self.progress.is_complete() {
+            match self.receiver.try_recv() {
+                Ok((index, chunk)) => self.apply(doc, index, chunk)?,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Err(disconnected()),
+            }
+        }
+        self.finish(doc);
+        Ok(self.progress)
+    }
+
+    /// Block until the tiles intersecting the current view are in `doc`
+    pub fn wait_visible(&mut self, doc: &mut Document) -> NativeResult<LoadProgress> {
+        self.poll(doc)?;
+        while !self.is_visible_complete() {
+            self.receive(doc)?;
+        }
+        self.finish(doc);
+        Ok(self.progress)
+    }
+
+    /// Block until every tile is in `doc`
+    pub fn wait(&mut self, doc: &mut Document) -> NativeResult<LoadProgress> {
+        while !self.progress.is_complete() {
+            self.receive(doc)?;
+        }
+        self.finish(doc);
+        Ok(self.progress)
+    }
+
+    fn receive(&mut self, doc: &mut Document) -> NativeResult<()> {
+        let (index, chunk) = self.receiver.recv().map_err(|_| disconnected())?;
+        self.apply(doc, index, chunk)
+    }
+
+    fn apply(&mut self, doc: &mut Document, index: usize, chunk: NativeResult<Chunk>) -> NativeResult<()> {
+        let chunk = chunk?;
+        self.progress.loaded_entities += chunk.len();
+        self.progress.loaded_tiles += 1;
+        self.loaded[index] = true;
+        for (ordinal, entity) in chunk {
+            self.ordinals.insert(entity.id, ordinal);
+            doc.add_entity(entity);
+        }
+        Ok(())
+    }
+
+    /// Restore file order once everything has arrived
+    fn finish(&mut self, doc: &mut Document) {
+        if self.finished || !self.progress.is_complete() {
+            return;
+        }
+        self.finished = true;
+        let ordinals = std::mem::take(&mut self.ordinals);
+        doc.entities
+            .sort_by_key(|e| ordinals.get(&e.id).copied().unwrap_or(u64::MAX));
+        doc.rebuild_spatial_index();
+    }
+}
+
+fn disconnected() -> NativeError {
+    NativeError::Deserialization("progressive load stopped before all tiles were read".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{GeometryType, Line};
+    use std::io::Cursor;
+
+    fn grid(n: usize) -> Document {
+        let mut doc = Document::new();
+        for i in 0..n * n {
+            let (x, y) = ((i % n) as f64 * 10.0, (i / n) as f64 * 10.0);
+            doc.add_entity(Entity::new(
+                GeometryType::Line(Line {
+                    start: Vec3::new(x, y, 0.0),
+                    end: Vec3::new(x + 1.0, y + 1.0, 0.0),
+                }),
+                "0".to_string(),
+            ));
+        }
+        doc
+    }
+
+    fn region(x0: f64, y0: f64, x1: f64, y1: f64) -> BoundingBox {
+        BoundingBox::new(Vec3::new(x0, y0, -1.0), Vec3::new(x1, y1, 1.0))
+    }
+
+    #[test]
+    fn test_visible_tiles_load_first_and_order_is_restored() {
+        let doc = grid(20);
+        let mut data = Vec::new();
+        TiledFormat::new().with_tile_size(25).write_to(&doc, &mut data).unwrap();
+
+        let loader = ProgressiveLoader::from_reader(Cursor::new(data.clone()), &NativeFormat::new()).unwrap();
+        assert!(loader.is_tiled());
+        assert_eq!(loader.entity_count(), 400);
+        assert_eq!(loader.tile_count(), 16);
+        assert!(loader.skeleton().entities.is_empty());
+        let extents = loader.extents().unwrap();
+        assert_eq!((extents.max.x, extents.max.y), (191.0, 191.0));
+
+        // The tile under a corner view arrives first
+        let view = region(160.0, 160.0, 175.0, 175.0);
+        let (mut loaded, mut load) = loader.start(view);
+        let (first, chunk) = load.receiver.recv().unwrap();
+        assert_eq!(gap(&load.bounds[first].unwrap(), &view), 0.0);
+        load.apply(&mut loaded, first, chunk).unwrap();
+        load.wait_visible(&mut loaded).unwrap();
+        assert!(load.is_visible_complete());
+        assert_eq!(loaded.entities_in_box(&view).len(), 4);
+
+        let progress = load.wait(&mut loaded).unwrap();
+        assert!(progress.is_complete());
+        assert_eq!(progress.fraction(), 1.0);
+        let ids = |d: &Document| d.entities.iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(ids(&loaded), ids(&doc));
+        assert_eq!(loaded.spatial_index().len(), 400);
+
+        // Plain native loading reads tiled files too
+        let whole = NativeFormat::new().read_from(data.as_slice()).unwrap();
+        assert_eq!(whole.id, doc.id);
+        assert_eq!(ids(&whole), ids(&doc));
+    }
+
+    #[test]
+    fn test_refocus_and_untiled_files() {
+        let doc = grid(12);
+        let mut data = Vec::new();
+        NativeFormat::new().write_to(&doc, &mut data).unwrap();
+
+        // Version 1 files are tiled in memory and delivered the same way
+        let loader = ProgressiveLoader::from_reader(Cursor::new(data), &NativeFormat::new()).unwrap();
+        assert!(!loader.is_tiled());
+        assert_eq!(loader.entity_count(), 144);
+
+        let (mut loaded, mut load) = loader.start(region(0.0, 0.0, 5.0, 5.0));
+        load.refocus(region(100.0, 100.0, 115.0, 115.0));
+        load.wait_visible(&mut loaded).unwrap();
+        assert_eq!(loaded.entities_in_box(&region(100.0, 100.0, 115.0, 115.0)).len(), 4);
+
+        // Entities added during the load stay after the file's own
+        let extra = grid(1).entities.remove(0);
+        let extra_id = loaded.add_entity(extra);
+        while !load.poll(&mut loaded).unwrap().is_complete() {
+            thread::yield_now();
+        }
+        assert_eq!(loaded.entities.len(), 145);
+        assert_eq!(loaded.entities.last().unwrap().id, extra_id);
+        assert_eq!(loaded.entities[0].id, doc.entities[0].id);
+    }
+}
@@ -0,0 +1,770 @@
+//! Tiled raster export
+//!
+//! GPU render targets are limited to [`MAX_TILE_SIZE`] pixels a side, which
+//! rules out single-pass renders of large site plans. Output is therefore
+//! rendered in tiles no larger than the limit and stitched one band (row of
+//! tiles) at a time, with each band streamed straight into the PNG or TIFF
+//! encoder. Memory use is bounded by a single band rather than the whole
+//! image, so 20,000 × 20,000 px site plans export on ordinary machines.
+//!
+//! The drawing is plotted onto a "paper" the size of the image at the export
+//! DPI, so lineweights come out at their true printed width: a 0.50 mm pen
+//! at 300 DPI is about 6 px wide whatever the image size. Every pixel is
+//! shaded from its position in the whole image, never from its position in a
+//! tile, so tiles meet without seams.
+//!
+//! Text is not rasterized; use SVG or PDF output when annotation matters.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::write::ZlibEncoder;
+use flate2::{Compression, Crc};
+
+use super::document::{Color, Document, PaperSize};
+use super::export::{ExportError, ExportResult, RasterExportSettings};
+use crate::sheets::plot::{plot_document, Orientation, PlotArea, PlotScale, PlotSettings};
+
+/// Largest tile edge, matching the renderer's texture limit
+pub const MAX_TILE_SIZE: u32 = 8192;
+
+/// Millimeters per inch
+const MM_PER_INCH: f64 = 25.4;
+
+/// Raster file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RasterFormat {
+    /// Portable Network Graphics (deflate compressed)
+    Png,
+    /// Baseline TIFF (uncompressed strips)
+    Tiff,
+}
+
+impl RasterFormat {
+    /// Format from a file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "png" => Some(RasterFormat::Png),
+            "tif" | "tiff" => Some(RasterFormat::Tiff),
+            _ => None,
+        }
+    }
+
+    /// World file extension (`.pgw` for PNG, `.tfw` for TIFF)
+    pub fn world_file_extension(self) -> &'static str {
+        match self {
+            RasterFormat::Png => "pgw",
+            RasterFormat::Tiff => "tfw",
+        }
+    }
+}
+
+/// What a raster export produced
+#[derive(Debug, Clone, PartialEq)]
+pub struct RasterOutput {
+    /// Image width in pixels
+    pub width: u32,
+    /// Image height in pixels
+    pub height: u32,
+    /// Tile columns
+    pub tiles_x: u32,
+    /// Tile rows
+    pub tiles_y: u32,
+    /// Drawing units per pixel
+    pub pixel_size: f64,
+    /// Drawing coordinates of the image's upper-left corner
+    pub origin: (f64, f64),
+    /// World file written next to the image
+    pub world_file: Option<PathBuf>,
+}
+
+impl RasterOutput {
+    /// ESRI world file contents
+    ///
+    /// The six lines are the pixel size in x, two rotation terms, the
+    /// (negative) pixel size in y, and the center of the upper-left pixel.
+    pub fn world_file_contents(&self) -> String {
+        let half = self.pixel_size / 2.0;
+        format!(
+            "{:.10}\n0.0000000000\n0.0000000000\n{:.10}\n{:.10}\n{:.10}\n",
+            self.pixel_size,
+            -self.pixel_size,
+            self.origin.0 + half,
+            self.origin.1 - half,
+        )
+    }
+}
+
+/// Render a document to an image file
+///
+/// The format comes from `format`, the world file (if enabled) is written
+/// next to `path` with the matching extension.
+pub fn export_raster<P: AsRef<Path>>(
+    doc: &Document,
+    settings: &RasterExportSettings,
+    path: P,
+    format: RasterFormat,
+) -> ExportResult<RasterOutput> {
+    let path = path.as_ref();
+    validate(settings)?;
+    let (width, height) = (settings.width, settings.height);
+    let writer = BufWriter::new(File::create(path)?);
+
+    let mut output = match format {
+        RasterFormat::Png => {
+            let mut png = PngWriter::new(writer, width, height, settings.dpi)?;
+            let output = render_rows(doc, settings, |rows| png.write_rows(rows))?;
+            png.finish()?;
+            output
+        }
+        RasterFormat::Tiff => {
+            let mut tiff =
+                TiffWriter::new(writer, width, height, band_height(settings), settings.dpi)?;
+            let output = render_rows(doc, settings, |rows| tiff.write_rows(rows))?;
+            tiff.finish()?;
+            output
+        }
+    };
+
+    if settings.world_file {
+        let world = path.with_extension(format.world_file_extension());
+        std::fs::write(&world, output.world_file_contents())?;
+        output.world_file = Some(world);
+    }
+    Ok(output)
+}
+
+/// Render a document band by band
+///
+/// `sink` receives packed RGB rows, top to bottom, one band of tiles at a
+/// time.
+pub fn render_rows<F>(
+    doc: &Document,
+    settings: &RasterExportSettings,
+    mut sink: F,
+) -> ExportResult<RasterOutput>
+where
+    F: FnMut(&[u8]) -> io::Result<()>,
+{
+    validate(settings)?;
+    let (width, height) = (settings.width, settings.height);
+    let tile = settings.tile_size.min(MAX_TILE_SIZE);
+    let px_per_mm = settings.dpi as f64 / MM_PER_INCH;
+
+    // Plot onto paper the size of the image at the export DPI
+    let (paper_w, paper_h) = (width as f64 / px_per_mm, height as f64 / px_per_mm);
+    let plot = PlotSettings {
+        paper: PaperSize::Custom {
+            width: paper_w,
+            height: paper_h,
+        },
+        orientation: if paper_w >= paper_h {
+            Orientation::Landscape
+        } else {
+            Orientation::Portrait
+        },
+        area: match settings.window {
+            Some((min, max)) => PlotArea::Window { min, max },
+            None => PlotArea::Extents,
+        },
+        scale: PlotScale::Fit,
+        margin: 0.0,
+        line_width: settings.line_width,
+    };
+    let page =
+        plot_document(doc, &plot, "raster").map_err(|e| ExportError::Rendering(e.to_string()))?;
+
+    let background = rgb(settings.background);
+    let segments = strokes(&page, height as f64, px_per_mm, settings);
+    let smooth = settings.anti_aliasing > 1;
+
+    let tiles_x = width.div_ceil(tile);
+    let tiles_y = height.div_ceil(tile);
+    let mut rows = Vec::new();
+    for ty in 0..tiles_y {
+        let y0 = ty * tile;
+        let band_h = tile.min(height - y0);
+
+        let render = |tx: u32| {
+            let x0 = tx * tile;
+            let rect = TileRect {
+                x0,
+                y0,
+                width: tile.min(width - x0),
+                height: band_h,
+            };
+            render_tile(&segments, rect, background, smooth)
+        };
+        #[cfg(feature = "parallel")]
+        let tiles: Vec<Vec<u8>> = {
+            use rayon::prelude::*;
+            (0..tiles_x).into_par_iter().map(render).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let tiles: Vec<Vec<u8>> = (0..tiles_x).map(render).collect();
+
+        // Stitch the band's tiles row by row
+        rows.clear();
+        rows.reserve(width as usize * band_h as usize * 3);
+        for row in 0..band_h as usize {
+            for (tx, pixels) in tiles.iter().enumerate() {
+                let stride = tile.min(width - tx as u32 * tile) as usize * 3;
+                rows.extend_from_slice(&pixels[row * stride..(row + 1) * stride]);
+            }
+        }
+        sink(&rows)?;
+    }
+
+    let pixel_size = 1.0 / (page.scale * px_per_mm);
+    Ok(RasterOutput {
+        width,
+        height,
+        tiles_x,
+        tiles_y,
+        pixel_size,
+        origin: (page.origin.0, page.origin.1 + height as f64 * pixel_size),
+        world_file: None,
+    })
+}
+
+/// Encode packed RGB rows, top to bottom, as an in-memory PNG
+pub fn encode_png(width: u32, height: u32, dpi: u32, rgb: &[u8]) -> io::Result<Vec<u8>> {
+    if rgb.len() != width as usize * height as usize * 3 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} bytes is not a {}x{} RGB image", rgb.len(), width, height),
+        ));
+    }
+    let mut out = Vec::new();
+    let mut png = PngWriter::new(&mut out, width, height, dpi)?;
+    png.write_rows(rgb)?;
+    png.finish()?;
+    Ok(out)
+}
+
+fn validate(settings: &RasterExportSettings) -> ExportResult<()> {
+    if settings.width == 0 || settings.height == 0 {
+        return Err(ExportError::InvalidSettings(format!(
+            "image size {}x{} must be non-zero",
+            settings.width, settings.height
+        )));
+    }
+    if settings.tile_size == 0 {
+        return Err(ExportError::InvalidSettings(
+            "tile size must be non-zero".to_string(),
+        ));
+    }
+    if settings.dpi == 0 {
+        return Err(ExportError::InvalidSettings(
+            "DPI must be non-zero".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn band_height(settings: &RasterExportSettings) -> u32 {
+    settings.tile_size.clamp(1, MAX_TILE_SIZE)
+}
+
+fn rgb(color: Color) -> [f32; 3] {
+    [
+        color.r as f32 / 255.0,
+        color.g as f32 / 255.0,
+        color.b as f32 / 255.0,
+    ]
+}
+
+/// Line segment in image pixels (y down)
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    a: (f64, f64),
+    b: (f64, f64),
+    half_width: f64,
+    color: [f32; 3],
+}
+
+impl Segment {
+    /// Pixel bounds, padded for the stroke and its antialiased edge
+    fn bounds(&self) -> (f64, f64, f64, f64) {
+        let pad = self.half_width + 1.0;
+        (
+            self.a.0.min(self.b.0) - pad,
+            self.a.1.min(self.b.1) - pad,
+            self.a.0.max(self.b.0) + pad,
+            self.a.1.max(self.b.1) + pad,
+        )
+    }
+
+    fn distance(&self, x: f64, y: f64) -> f64 {
+        let (dx, dy) = (self.b.0 - self.a.0, self.b.1 - self.a.1);
+        let len2 = dx * dx + dy * dy;
+        let t = if len2 > 0.0 {
+            (((x - self.a.0) * dx + (y - self.a.1) * dy) / len2).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        (x - self.a.0 - t * dx).hypot(y - self.a.1 - t * dy)
+    }
+}
+
+/// Page paths as pixel segments
+fn strokes(
+    page: &crate::sheets::plot::PlotPage,
+    height: f64,
+    px_per_mm: f64,
+    settings: &RasterExportSettings,
+) -> Vec<Segment> {
+    let to_px = |(x, y): (f64, f64)| (x * px_per_mm, height - y * px_per_mm);
+    let mut segments = Vec::new();
+    for path in &page.paths {
+        // Lineweights are printed widths; hairlines are one pixel
+        let weight = path.weight.unwrap_or(settings.line_width);
+        let half_width = (weight * px_per_mm).max(1.0) / 2.0;
+        // Like color 7, pens matching the background plot in its contrast
+        let color = if path.color == settings.background {
+            let luma = path.color.r as u32 * 3 + path.color.g as u32 * 6 + path.color.b as u32;
+            if luma > 1275 {
+                [0.0; 3]
+            } else {
+                [1.0; 3]
+            }
+        } else {
+            rgb(path.color)
+        };
+
+        let points: Vec<(f64, f64)> = path.points.iter().copied().map(to_px).collect();
+        let closing = if path.closed && points.len() > 2 {
+            Some((points[points.len() - 1], points[0]))
+        } else {
+            None
+        };
+        for (a, b) in points.windows(2).map(|w| (w[0], w[1])).chain(closing) {
+            segments.push(Segment {
+                a,
+                b,
+                half_width,
+                color,
+            });
+        }
+    }
+    segments
+}
+
+/// Tile position and size in image pixels
+#[derive(Debug, Clone, Copy)]
+struct TileRect {
+    x0: u32,
+    y0: u32,
+    width: u32,
+    height: u32,
+}
+
+fn render_tile(
+    segments: &[Segment],
+    rect: TileRect,
+    background: [f32; 3],
+    smooth: bool,
+) -> Vec<u8> {
+    let (w, h) = (rect.width as usize, rect.height as usize);
+    let mut pixels = vec![background; w * h];
+    let (tx0, ty0) = (rect.x0 as f64, rect.y0 as f64);
+    let (tx1, ty1) = (tx0 + w as f64, ty0 + h as f64);
+
+    for segment in segments {
+        let (min_x, min_y, max_x, max_y) = segment.bounds();
+        if max_x < tx0 || min_x > tx1 || max_y < ty0 || min_y > ty1 {
+            continue;
+        }
+        let x_range = (min_x.max(tx0).floor() as usize - rect.x0 as usize)
+            ..(max_x.min(tx1).ceil() as usize - rect.x0 as usize).min(w);
+        let y_range = (min_y.max(ty0).floor() as usize - rect.y0 as usize)
+            ..(max_y.min(ty1).ceil() as usize - rect.y0 as usize).min(h);
+
+        for py in y_range {
+            // Sample at the pixel center in whole-image coordinates
+            let y = ty0 + py as f64 + 0.5;
+            for px in x_range.clone() {
+                let x = tx0 + px as f64 + 0.5;
+                let d = segment.distance(x, y);
+                let coverage = if smooth {
+                    (segment.half_width + 0.5 - d).clamp(0.0, 1.0) as f32
+                } else if d <= segment.half_width {
+                    1.0
+                } else {
+                    0.0
+                };
+                if coverage > 0.0 {
+                    let dst = &mut pixels[py * w + px];
+                    for (d, s) in dst.iter_mut().zip(segment.color) {
+                        *d += (s - *d) * coverage;
+                    }
+                }
+            }
+        }
+    }
+    pixels
+        .iter()
+        .flat_map(|p| p.map(|c| (c * 255.0).round().clamp(0.0, 255.0) as u8))
+        .collect()
+}
+
+/// Streaming PNG encoder (8-bit RGB, no row filtering)
+struct PngWriter<W: Write> {
+    out: W,
+    row_bytes: usize,
+    encoder: ZlibEncoder<Vec<u8>>,
+}
+
+impl<W: Write> PngWriter<W> {
+    fn new(mut out: W, width: u32, height: u32, dpi: u32) -> io::Result<Self> {
+        out.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend_from_slice(&width.to_be_bytes());
+        ihdr.extend_from_slice(&height.to_be_bytes());
+        ihdr.extend_from_slice(&[8, 2, 0, 0, 0]); // 8-bit RGB, deflate, no filter, no interlace
+        write_png_chunk(&mut out, b"IHDR", &ihdr)?;
+
+        let ppm = (dpi as f64 / 0.0254).round() as u32;
+        let mut phys = Vec::with_capacity(9);
+        phys.extend_from_slice(&ppm.to_be_bytes());
+        phys.extend_from_slice(&ppm.to_be_bytes());
+        phys.push(1); // meters
+        write_png_chunk(&mut out, b"pHYs", &phys)?;
+
+        Ok(Self {
+            out,
+            row_bytes: width as usize * 3,
+            encoder: ZlibEncoder::new(Vec::new(), Compression::default()),
+        })
+    }
+
+    fn write_rows(&mut self, rows: &[u8]) -> io::Result<()> {
+        for row in rows.chunks(self.row_bytes) {
+            self.encoder.write_all(&[0])?;
+            self.encoder.write_all(row)?;
+        }
+        // Flush what the compressor has produced so far as an IDAT chunk
+        let compressed = std::mem::take(self.encoder.get_mut());
+        if !compressed.is_empty() {
+            write_png_chunk(&mut self.out, b"IDAT", &compressed)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        let rest = self.encoder.finish()?;
+        if !rest.is_empty() {
+            write_png_chunk(&mut self.out, b"IDAT", &rest)?;
+        }
+        write_png_chunk(&mut self.out, b"IEND", &[])?;
+        self.out.flush()
+    }
+}
+
+fn write_png_chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let mut crc = Crc::new();
+    crc.update(kind);
+    crc.update(data);
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    out.write_all(&crc.sum().to_be_bytes())
+}
+
+/// Streaming baseline TIFF encoder (8-bit RGB, one strip per band)
+///
+/// The pixel data size is known up front, so the directory can go after
+/// the strips without seeking back.
+struct TiffWriter<W: Write> {
+    out: W,
+    width: u32,
+    height: u32,
+    rows_per_strip: u32,
+    dpi: u32,
+    data_len: u32,
+}
+
+impl<W: Write> TiffWriter<W> {
+    fn new(
+        mut out: W,
+        width: u32,
+        height: u32,
+        rows_per_strip: u32,
+        dpi: u32,
+    ) -> ExportResult<Self> {
+        let data_len = width as u64 * height as u64 * 3;
+        if data_len + 1024 > u32::MAX as u64 {
+            return Err(ExportError::InvalidSettings(format!(
+                "{}x{} exceeds the 4 GB TIFF limit, export PNG instead",
+                width, height
+            )));
+        }
+        let data_len = data_len as u32;
+        let rows_per_strip = rows_per_strip.clamp(1, height);
+        let ifd_offset = 8 + data_len + data_len % 2;
+
+        out.write_all(b"II")?;
+        out.write_all(&42u16.to_le_bytes())?;
+        out.write_all(&ifd_offset.to_le_bytes())?;
+        Ok(Self {
+            out,
+            width,
+            height,
+            rows_per_strip,
+            dpi,
+            data_len,
+        })
+    }
+
+    fn write_rows(&mut self, rows: &[u8]) -> io::Result<()> {
+        self.out.write_all(rows)
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        if self.data_len % 2 == 1 {
+            self.out.write_all(&[0])?; // Directory must start on a word boundary
+        }
+
+        let strips = self.height.div_ceil(self.rows_per_strip);
+        let strip_bytes = self.width * self.rows_per_strip * 3;
+        let offsets: Vec<u32> = (0..strips).map(|i| 8 + i * strip_bytes).collect();
+        let counts: Vec<u32> = (0..strips)
+            .map(|i| {
+                let rows = self
+                    .rows_per_strip
+                    .min(self.height - i * self.rows_per_strip);
+                self.width * rows * 3
+            })
+            .collect();
+
+        // Out-of-line values follow the directory
+        const ENTRIES: u32 = 13;
+        let ifd_offset = 8 + self.data_len + self.data_len % 2;
+        let extra = ifd_offset + 2 + ENTRIES * 12 + 4;
+        let bits_offset = extra;
+        let x_res_offset = bits_offset + 6;
+        let y_res_offset = x_res_offset + 8;
+        let offsets_offset = y_res_offset + 8;
+        let counts_offset = offsets_offset + 4 * strips;
+        // A single value fits in the entry itself
+        let inline_or = |values: &[u32], at: u32| if values.len() == 1 { values[0] } else { at };
+
+        const SHORT: u16 = 3;
+        const LONG: u16 = 4;
+        const RATIONAL: u16 = 5;
+        let entries: [(u16, u16, u32, u32); ENTRIES as usize] = [
+            (256, LONG, 1, self.width),                               // ImageWidth
+            (257, LONG, 1, self.height),                              // ImageLength
+            (258, SHORT, 3, bits_offset),                             // BitsPerSample
+            (259, SHORT, 1, 1),                                       // Compression: none
+            (262, SHORT, 1, 2),                                       // Photometric: RGB
+            (273, LONG, strips, inline_or(&offsets, offsets_offset)), // StripOffsets
+            (277, SHORT, 1, 3),                                       // SamplesPerPixel
+            (278, LONG, 1, self.rows_per_strip),                      // RowsPerStrip
+            (279, LONG, strips, inline_or(&counts, counts_offset)),   // StripByteCounts
+            (282, RATIONAL, 1, x_res_offset),                         // XResolution
+            (283, RATIONAL, 1, y_res_offset),                         // YResolution
+            (284, SHORT, 1, 1),                                       // PlanarConfiguration: chunky
+            (296, SHORT, 1, 2),                                       // ResolutionUnit: inch
+        ];
+
+        self.out.write_all(&(ENTRIES as u16).to_le_bytes())?;
+        for (tag, kind, count, value) in entries {
+            self.out.write_all(&tag.to_le_bytes())?;
+            self.out.write_all(&kind.to_le_bytes())?;
+            self.out.write_all(&count.to_le_bytes())?;
+            if kind == SHORT && count == 1 {
+                // Short values are left-justified in the 4-byte field
+                self.out.write_all(&(value as u16).to_le_bytes())?;
+                self.out.write_all(&[0, 0])?;
+            } else {
+                self.out.write_all(&value.to_le_bytes())?;
+            }
+        }
+        self.out.write_all(&0u32.to_le_bytes())?; // No further directories
+
+        for bits in [8u16; 3] {
+            self.out.write_all(&bits.to_le_bytes())?;
+        }
+        for _ in 0..2 {
+            self.out.write_all(&self.dpi.to_le_bytes())?;
+            self.out.write_all(&1u32.to_le_bytes())?;
+        }
+        if strips > 1 {
+            for value in offsets.iter().chain(&counts) {
+                self.out.write_all(&value.to_le_bytes())?;
+            }
+        }
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{Entity, GeometryType, Layer, Line, LineType, LineWeight, Vec3};
+
+    fn line(x1: f64, y1: f64, x2: f64, y2: f64) -> Entity {
+        Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::new(x1, y1, 0.0),
+                end: Vec3::new(x2, y2, 0.0),
+            }),
+            "0".to_string(),
+        )
+    }
+
+    fn settings(width: u32, height: u32) -> RasterExportSettings {
+        RasterExportSettings {
+            width,
+            height,
+            window: Some(((0.0, 0.0), (width as f64, height as f64))),
+            dpi: 254, // 10 px per millimeter
+            ..RasterExportSettings::default()
+        }
+    }
+
+    fn render(doc: &Document, settings: &RasterExportSettings) -> (RasterOutput, Vec<u8>) {
+        let mut image = Vec::new();
+        let output = render_rows(doc, settings, |rows| {
+            image.extend_from_slice(rows);
+            Ok(())
+        })
+        .unwrap();
+        (output, image)
+    }
+
+    fn diagonal_doc() -> Document {
+        let mut doc = Document::new();
+        doc.add_entity(line(3.0, 7.0, 97.0, 61.0));
+        doc.add_entity(line(50.0, 0.0, 50.0, 80.0));
+        doc
+    }
+
+    #[test]
+    fn test_tiles_stitch_without_seams() {
+        let doc = diagonal_doc();
+        let whole = settings(100, 80);
+        let tiled = RasterExportSettings {
+            tile_size: 7,
+            ..whole.clone()
+        };
+
+        let (one, reference) = render(&doc, &whole);
+        let (many, stitched) = render(&doc, &tiled);
+        assert_eq!((one.tiles_x, one.tiles_y), (1, 1));
+        assert_eq!((many.tiles_x, many.tiles_y), (15, 12));
+        assert_eq!(stitched.len(), 100 * 80 * 3);
+        assert_eq!(stitched, reference);
+    }
+
+    #[test]
+    fn test_lineweight_scales_with_dpi() {
+        let mut doc = Document::new();
+        doc.add_layer(Layer {
+            name: "HEAVY".to_string(),
+            color: Color::black(),
+            line_type: LineType::Continuous,
+            line_weight: LineWeight::Width(100), // 1.00 mm
+            visible: true,
+            locked: false,
+            frozen: false,
+            plottable: true,
+            confidential: false,
+        });
+        let mut heavy = line(0.0, 40.0, 100.0, 40.0);
+        heavy.layer = "HEAVY".to_string();
+        doc.add_entity(heavy);
+
+        // Dark pixels down column 50
+        let dark_rows = |settings: &RasterExportSettings| {
+            let (_, image) = render(&doc, settings);
+            let width = settings.width as usize;
+            (0..settings.height as usize)
+                .filter(|y| image[(y * width + 50) * 3] < 128)
+                .count()
+        };
+
+        let mut s = settings(100, 80);
+        s.anti_aliasing = 1;
+        assert_eq!(dark_rows(&s), 10); // 1 mm at 10 px/mm
+
+        // Same drawing window, twice the pixels and DPI: the pen doubles too
+        s.width = 200;
+        s.height = 160;
+        s.dpi = 508;
+        assert_eq!(dark_rows(&s), 20);
+    }
+
+    #[test]
+    fn test_png_and_tiff_files() {
+        let doc = diagonal_doc();
+        let dir = std::env::temp_dir().join(format!("raster-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let s = RasterExportSettings {
+            tile_size: 32,
+            ..settings(100, 80)
+        };
+        let (_, reference) = render(&doc, &s);
+
+        for (name, format) in [
+            ("plan.png", RasterFormat::Png),
+            ("plan.tif", RasterFormat::Tiff),
+        ] {
+            let path = dir.join(name);
+            assert_eq!(RasterFormat::from_path(&path), Some(format));
+            export_raster(&doc, &s, &path, format).unwrap();
+
+            let decoded = image::open(&path).unwrap().to_rgb8();
+            assert_eq!(decoded.dimensions(), (100, 80));
+            assert_eq!(decoded.into_raw(), reference, "{}", name);
+        }
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_world_file() {
+        let doc = diagonal_doc();
+        let dir = std::env::temp_dir().join(format!("raster-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // 200 x 80 units on 100 x 40 px: 2 units per pixel
+        let s = RasterExportSettings {
+            width: 100,
+            height: 40,
+            window: Some(((0.0, 0.0), (200.0, 80.0))),
+            world_file: true,
+            ..RasterExportSettings::default()
+        };
+        let output = export_raster(&doc, &s, dir.join("site.png"), RasterFormat::Png).unwrap();
+        assert!((output.pixel_size - 2.0).abs() < 1e-9);
+        assert!((output.origin.0 - 0.0).abs() < 1e-9);
+        assert!((output.origin.1 - 80.0).abs() < 1e-9);
+
+        let world = output.world_file.unwrap();
+        assert_eq!(world.extension().unwrap(), "pgw");
+        let lines: Vec<f64> = std::fs::read_to_string(&world)
+            .unwrap()
+            .lines()
+            .map(|l| l.parse().unwrap())
+            .collect();
+        assert_eq!(lines, vec![2.0, 0.0, 0.0, -2.0, 1.0, 79.0]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_invalid_settings() {
+        let doc = diagonal_doc();
+        let mut s = settings(0, 10);
+        assert!(matches!(
+            render_rows(&doc, &s, |_| Ok(())),
+            Err(ExportError::InvalidSettings(_))
+        ));
+        s.width = 10;
+        s.tile_size = 0;
+        assert!(render_rows(&doc, &s, |_| Ok(())).is_err());
+    }
+}
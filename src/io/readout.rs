@@ -0,0 +1,535 @@
+// CADDY - Enterprise CAD System
+// File I/O System - Coordinate Readout Formats
+
+//! Coordinate, distance and angle readout formats
+//!
+//! [`ReadoutFormat`] controls how values are written wherever a user reads
+//! them back: the status bar, dimension text and inquiry commands.
+//!
+//! - Points as cartesian `X, Y`, polar `distance<angle` from a base point, or
+//!   station/offset along an [`Alignment`] (`12+34.56, 5.00 R`)
+//! - Distances as decimal, fractional inches, architectural `1'-6 1/2"` or
+//!   engineering `1'-6.50"`
+//! - Angles as decimal degrees, degrees/minutes/seconds, gradians, radians,
+//!   or surveyor bearings (`N45°30'E`)
+//!
+//! ## Example
+//! ```
+//! use caddy::io::readout::{AngleFormat, ReadoutFormat, StationFormat};
+//!
+//! let readout = ReadoutFormat {
+//!     angle: AngleFormat::Bearing,
+//!     angle_precision: 2,
+//!     ..ReadoutFormat::default()
+//! };
+//! assert_eq!(readout.format_direction(45.5_f64.to_radians()), "N44°30'E");
+//! assert_eq!(StationFormat::default().format(1234.5), "12+34.50");
+//! ```
+
+use crate::geometry::Point2D;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Largest fractional denominator exponent (1/256)
+const MAX_FRACTION_BITS: usize = 8;
+
+/// How points are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CoordinateMode {
+    /// `X, Y`
+    #[default]
+    Cartesian,
+    /// `distance<angle` from the base point
+    Polar,
+    /// Station and offset along an alignment
+    StationOffset,
+}
+
+impl CoordinateMode {
+    /// Next mode, for toggling the readout
+    pub fn next(self) -> Self {
+        match self {
+            CoordinateMode::Cartesian => CoordinateMode::Polar,
+            CoordinateMode::Polar => CoordinateMode::StationOffset,
+            CoordinateMode::StationOffset => CoordinateMode::Cartesian,
+        }
+    }
+
+    /// Short label for the status bar
+    pub fn label(self) -> &'static str {
+        match self {
+            CoordinateMode::Cartesian => "XY",
+            CoordinateMode::Polar => "POLAR",
+            CoordinateMode::StationOffset => "STA",
+        }
+    }
+}
+
+/// How distances are written
+///
+/// Fractional, architectural and engineering formats treat one drawing
+/// unit as an inch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DistanceFormat {
+    /// `18.5000`
+    #[default]
+    Decimal,
+    /// `18 1/2`
+    Fractional,
+    /// `1'-6 1/2"`
+    Architectural,
+    /// `1'-6.5000"`
+    Engineering,
+}
+
+/// How angles are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AngleFormat {
+    /// `45.5°`
+    #[default]
+    DecimalDegrees,
+    /// `45°30'00"`
+    DegMinSec,
+    /// `50.5556g`
+    Gradians,
+    /// `0.7941r`
+    Radians,
+    /// Surveyor bearing, `N44°30'E`; plain angles fall back to DMS
+    Bearing,
+}
+
+/// Civil station format, e.g. `12+34.56` for 1234.56 with 100-unit stations
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StationFormat {
+    /// Distance between full stations (100 for feet, 1000 for metres)
+    pub interval: f64,
+    /// Decimal places after the station plus
+    pub precision: usize,
+}
+
+impl Default for StationFormat {
+    fn default() -> Self {
+        Self {
+            interval: 100.0,
+            precision: 2,
+        }
+    }
+}
+
+impl StationFormat {
+    /// Kilometre stations, `1+234.567`
+    pub fn metric() -> Self {
+        Self {
+            interval: 1000.0,
+            precision: 3,
+        }
+    }
+
+    /// Format a station value
+    pub fn format(&self, station: f64) -> String {
+        let scale = 10f64.powi(self.precision as i32);
+        let interval = ((self.interval * scale).round() as i64).max(1);
+        let scaled = (station.abs() * scale).round() as i64;
+        let (full, rest) = (scaled / interval, scaled % interval);
+
+        // Pad the remainder to the interval's digits, e.g. "05.00" for 100s
+        let digits = ((interval as f64 / scale).round() as i64 - 1).max(1).to_string().len();
+        let width = if self.precision > 0 {
+            digits + 1 + self.precision
+        } else {
+            digits
+        };
+        let sign = if station < 0.0 && scaled > 0 { "-" } else { "" };
+        format!(
+            "{}{}+{:0width$.prec$}",
+            sign,
+            full,
+            rest as f64 / scale,
+            width = width,
+            prec = self.precision
+        )
+    }
+}
+
+/// Horizontal alignment that stations are measured along
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alignment {
+    /// Centreline vertices in drawing coordinates
+    pub vertices: Vec<Point2D>,
+    /// Station at the first vertex
+    pub start_station: f64,
+}
+
+impl Alignment {
+    /// Create an alignment
+    pub fn new(vertices: Vec<Point2D>, start_station: f64) -> Self {
+        Self {
+            vertices,
+            start_station,
+        }
+    }
+
+    /// Total length
+    pub fn length(&self) -> f64 {
+        self.vertices
+            .windows(2)
+            .map(|pair| pair[0].distance_to(&pair[1]))
+            .sum()
+    }
+
+    /// Station and offset of `point`; offsets are positive to the left
+    pub fn station_offset(&self, point: Point2D) -> Option<(f64, f64)> {
+        let mut best: Option<(f64, f64)> = None;
+        let mut travelled = 0.0;
+
+        for pair in self.vertices.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            let direction = end - start;
+            let length = start.distance_to(&end);
+            if length > 0.0 {
+                let t = ((point - start).dot(&direction) / (length * length)).clamp(0.0, 1.0);
+                let foot = start + direction * t;
+                let distance = foot.distance_to(&point);
+                if best.is_none_or(|(_, offset)| distance < offset.abs()) {
+                    let side = direction.cross(&(point - start)).signum();
+                    best = Some((self.start_station + travelled + t * length, side * distance));
+                }
+            }
+            travelled += length;
+        }
+
+        best
+    }
+
+    /// Point at `station`, `offset` to the left of the centreline
+    pub fn point_at(&self, station: f64, offset: f64) -> Option<Point2D> {
+        let mut remaining = station - self.start_station;
+        let segments: Vec<_> = self.vertices.windows(2).collect();
+        for (i, pair) in segments.iter().enumerate() {
+            let length = pair[0].distance_to(&pair[1]);
+            if length > 0.0 && (remaining <= length || i == segments.len() - 1) {
+                let direction = (pair[1] - pair[0]) / length;
+                let left = Point2D::new(-direction.y, direction.x);
+                return Some(pair[0] + direction * remaining + left * offset);
+            }
+            remaining -= length;
+        }
+        None
+    }
+}
+
+/// Readout settings for points, distances and angles
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadoutFormat {
+    /// Point display
+    pub coordinates: CoordinateMode,
+    /// Distance display
+    pub distance: DistanceFormat,
+    /// Decimal places, or the power of two of the smallest fraction
+    /// (4 = 1/16) for fractional formats
+    pub precision: usize,
+    /// Angle display
+    pub angle: AngleFormat,
+    /// Decimal places for decimal angles; for DMS and bearings 0 shows
+    /// degrees, 1-2 add minutes, 3-4 add seconds, and more add decimal
+    /// seconds
+    pub angle_precision: usize,
+    /// Station display
+    pub station: StationFormat,
+}
+
+impl Default for ReadoutFormat {
+    fn default() -> Self {
+        Self {
+            coordinates: CoordinateMode::Cartesian,
+            distance: DistanceFormat::Decimal,
+            precision: 4,
+            angle: AngleFormat::DecimalDegrees,
+            angle_precision: 0,
+            station: StationFormat::default(),
+        }
+    }
+}
+
+impl ReadoutFormat {
+    /// Format a distance or coordinate value
+    pub fn format_distance(&self, value: f64) -> String {
+        let sign = if value < 0.0 { "-" } else { "" };
+        let value = value.abs();
+
+        let body = match self.distance {
+            DistanceFormat::Decimal => format!("{:.*}", self.precision, value),
+            DistanceFormat::Fractional => self.fraction(value),
+            DistanceFormat::Architectural => {
+                let (feet, inches) = self.feet_inches(value, |inches| self.fraction(inches));
+                format!("{}'-{}\"", feet, inches)
+            }
+            DistanceFormat::Engineering => {
+                let (feet, inches) =
+                    self.feet_inches(value, |inches| format!("{:.*}", self.precision, inches));
+                format!("{}'-{}\"", feet, inches)
+            }
+        };
+
+        // Don't show "-0"
+        if body.chars().all(|c| !c.is_ascii_digit() || c == '0') {
+            body
+        } else {
+            format!("{}{}", sign, body)
+        }
+    }
+
+    /// Format an angle value, such as an angular dimension
+    pub fn format_angle(&self, radians: f64) -> String {
+        let degrees = radians.to_degrees();
+        match self.angle {
+            AngleFormat::DecimalDegrees => format!("{:.*}°", self.angle_precision, degrees),
+            AngleFormat::DegMinSec | AngleFormat::Bearing => {
+                let sign = if degrees < 0.0 { "-" } else { "" };
+                format!("{}{}", sign, dms(degrees.abs(), self.angle_precision))
+            }
+            AngleFormat::Gradians => format!("{:.*}g", self.angle_precision, degrees / 0.9),
+            AngleFormat::Radians => format!("{:.*}r", self.angle_precision, radians),
+        }
+    }
+
+    /// Format a direction measured counterclockwise from the +X axis;
+    /// bearings are written from north or south toward east or west
+    pub fn format_direction(&self, radians: f64) -> String {
+        let direction = radians.rem_euclid(2.0 * PI);
+        if self.angle != AngleFormat::Bearing {
+            return self.format_angle(direction);
+        }
+
+        let azimuth = (90.0 - direction.to_degrees()).rem_euclid(360.0);
+        let (from, angle, toward) = if azimuth <= 90.0 {
+            ('N', azimuth, 'E')
+        } else if azimuth <= 180.0 {
+            ('S', 180.0 - azimuth, 'E')
+        } else if azimuth <= 270.0 {
+            ('S', azimuth - 180.0, 'W')
+        } else {
+            ('N', 360.0 - azimuth, 'W')
+        };
+
+        let text = dms(angle, self.angle_precision);
+        if text == dms(0.0, self.angle_precision) {
+            from.to_string()
+        } else if text == dms(90.0, self.angle_precision) {
+            toward.to_string()
+        } else {
+            format!("{}{}{}", from, text, toward)
+        }
+    }
+
+    /// Format a station/offset pair, e.g. `12+34.56, 5.0000 R`
+    pub fn format_station_offset(&self, station: f64, offset: f64) -> String {
+        let side = if offset > 0.0 {
+            " L"
+        } else if offset < 0.0 {
+            " R"
+        } else {
+            ""
+        };
+        format!(
+            "{}, {}{}",
+            self.station.format(station),
+            self.format_distance(offset.abs()),
+            side
+        )
+    }
+
+    /// Format a point in the current coordinate mode
+    ///
+    /// Polar readouts are relative to `base` (the origin if `None`).
+    /// Station/offset falls back to cartesian without an alignment.
+    pub fn format_point(
+        &self,
+        point: Point2D,
+        base: Option<Point2D>,
+        alignment: Option<&Alignment>,
+    ) -> String {
+        match self.coordinates {
+            CoordinateMode::Polar => {
+                let delta = point - base.unwrap_or_default();
+                format!(
+                    "{}<{}",
+                    self.format_distance(delta.x.hypot(delta.y)),
+                    self.format_direction(delta.y.atan2(delta.x))
+                )
+            }
+            CoordinateMode::StationOffset => {
+                match alignment.and_then(|alignment| alignment.station_offset(point)) {
+                    Some((station, offset)) => self.format_station_offset(station, offset),
+                    None => self.format_cartesian(point),
+                }
+            }
+            CoordinateMode::Cartesian => self.format_cartesian(point),
+        }
+    }
+
+    fn format_cartesian(&self, point: Point2D) -> String {
+        format!(
+            "{}, {}",
+            self.format_distance(point.x),
+            self.format_distance(point.y)
+        )
+    }
+
+    /// `whole num/den`, rounded to 1/2^precision and reduced
+    fn fraction(&self, value: f64) -> String {
+        let denominator = 1i64 << self.precision.min(MAX_FRACTION_BITS);
+        let total = (value * denominator as f64).round() as i64;
+        let (whole, mut numerator) = (total / denominator, total % denominator);
+        if numerator == 0 {
+            return whole.to_string();
+        }
+        let mut denominator = denominator;
+        while numerator % 2 == 0 {
+            numerator /= 2;
+            denominator /= 2;
+        }
+        if whole == 0 {
+            format!("{}/{}", numerator, denominator)
+        } else {
+            format!("{} {}/{}", whole, numerator, denominator)
+        }
+    }
+
+    /// Split inches into feet and formatted inches, carrying a rounded-up
+    /// 12" into the feet
+    fn feet_inches(&self, value: f64, inches: impl Fn(f64) -> String) -> (i64, String) {
+        let feet = (value / 12.0).floor();
+        let text = inches(value - feet * 12.0);
+        if text.parse::<f64>().is_ok_and(|v| v >= 12.0) {
+            (feet as i64 + 1, inches(0.0))
+        } else {
+            (feet as i64, text)
+        }
+    }
+}
+
+/// Degrees/minutes/seconds with AutoCAD-style precision steps
+fn dms(degrees: f64, precision: usize) -> String {
+    match precision {
+        0 => format!("{}°", degrees.round() as i64),
+        1 | 2 => {
+            let minutes = (degrees * 60.0).round() as i64;
+            format!("{}°{:02}'", minutes / 60, minutes % 60)
+        }
+        _ => {
+            let decimals = precision.saturating_sub(4);
+            let scale = 10f64.powi(decimals as i32);
+            let seconds = (degrees * 3600.0 * scale).round() / scale;
+            let whole = seconds.floor() as i64;
+            let fraction = seconds - whole as f64;
+            let width = if decimals > 0 { 3 + decimals } else { 2 };
+            format!(
+                "{}°{:02}'{:0width$.prec$}\"",
+                whole / 3600,
+                whole % 3600 / 60,
+                (whole % 60) as f64 + fraction,
+                width = width,
+                prec = decimals
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_formats() {
+        let mut readout = ReadoutFormat {
+            precision: 2,
+            ..ReadoutFormat::default()
+        };
+        assert_eq!(readout.format_distance(-1.23456), "-1.23");
+        assert_eq!(readout.format_distance(-0.001), "0.00");
+
+        readout.precision = 4;
+        readout.distance = DistanceFormat::Fractional;
+        assert_eq!(readout.format_distance(18.5), "18 1/2");
+        assert_eq!(readout.format_distance(0.3125), "5/16");
+
+        readout.distance = DistanceFormat::Architectural;
+        assert_eq!(readout.format_distance(18.5), "1'-6 1/2\"");
+        assert_eq!(readout.format_distance(23.999), "2'-0\"");
+
+        readout.distance = DistanceFormat::Engineering;
+        readout.precision = 2;
+        assert_eq!(readout.format_distance(18.5), "1'-6.50\"");
+    }
+
+    #[test]
+    fn test_angle_and_bearing_formats() {
+        let mut readout = ReadoutFormat {
+            angle: AngleFormat::DegMinSec,
+            angle_precision: 4,
+            ..ReadoutFormat::default()
+        };
+        assert_eq!(readout.format_angle(45.5_f64.to_radians()), "45°30'00\"");
+        assert_eq!(readout.format_angle(10.25_f64.to_radians()), "10°15'00\"");
+
+        readout.angle = AngleFormat::Bearing;
+        readout.angle_precision = 2;
+        // 44.5° from north toward east
+        assert_eq!(readout.format_direction(45.5_f64.to_radians()), "N44°30'E");
+        assert_eq!(readout.format_direction(200.0_f64.to_radians()), "S70°00'W");
+        assert_eq!(readout.format_direction(-30.0_f64.to_radians()), "S60°00'E");
+        assert_eq!(readout.format_direction(PI / 2.0), "N");
+        assert_eq!(readout.format_direction(PI), "W");
+    }
+
+    #[test]
+    fn test_station_offset() {
+        assert_eq!(StationFormat::default().format(1234.567), "12+34.57");
+        assert_eq!(StationFormat::default().format(5.0), "0+05.00");
+        assert_eq!(StationFormat::default().format(199.999), "2+00.00");
+        assert_eq!(StationFormat::metric().format(1234.5), "1+234.500");
+
+        let alignment = Alignment::new(
+            vec![Point2D::new(0.0, 0.0), Point2D::new(100.0, 0.0), Point2D::new(100.0, 100.0)],
+            1000.0,
+        );
+        let (station, offset) = alignment.station_offset(Point2D::new(40.0, -5.0)).unwrap();
+        assert!((station - 1040.0).abs() < 1e-9);
+        assert!((offset + 5.0).abs() < 1e-9);
+        let (station, offset) = alignment.station_offset(Point2D::new(90.0, 50.0)).unwrap();
+        assert!((station - 1150.0).abs() < 1e-9);
+        assert!((offset - 10.0).abs() < 1e-9);
+        assert!(alignment
+            .point_at(1150.0, 10.0)
+            .unwrap()
+            .approx_eq(&Point2D::new(90.0, 50.0)));
+
+        let readout = ReadoutFormat {
+            coordinates: CoordinateMode::StationOffset,
+            precision: 2,
+            ..ReadoutFormat::default()
+        };
+        assert_eq!(
+            readout.format_point(Point2D::new(40.0, -5.0), None, Some(&alignment)),
+            "10+40.00, 5.00 R"
+        );
+        assert_eq!(
+            readout.format_point(Point2D::new(40.0, -5.0), None, None),
+            "40.00, -5.00"
+        );
+    }
+
+    #[test]
+    fn test_polar_point() {
+        let readout = ReadoutFormat {
+            coordinates: CoordinateMode::Polar,
+            precision: 3,
+            ..ReadoutFormat::default()
+        };
+        assert_eq!(
+            readout.format_point(Point2D::new(4.0, 5.0), Some(Point2D::new(1.0, 1.0)), None),
+            "5.000<53°"
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Redaction of confidential content on export
+//!
+//! Layers flagged [`Layer::confidential`](super::document::Layer), plus any
+//! named in a [`RedactionProfile`], are withheld from drawings sent to
+//! external parties. [`RedactionProfile::redact`] splits a document into a
+//! shared part and a withheld part. Exporters write the shared part; when
+//! the profile asks for rasterizing and the output can carry images (PDF),
+//! the withheld part is plotted as a bitmap, so it stays visible while its
+//! geometry, text and layer names cannot be recovered. DXF has no
+//! self-contained raster, so DXF output always strips.
+//!
+//! Withheld entities inside block definitions are removed from the shared
+//! copy of the block as well. Only top-level entities are rasterized; a
+//! withheld entity nested in a block inserted on a shared layer is
+//! stripped.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::document::{Document, DocumentMetadata};
+
+/// What happens to withheld layers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RedactionAction {
+    /// Leave the content out entirely
+    #[default]
+    Strip,
+    /// Replace the content with a bitmap where the format allows it
+    Rasterize,
+}
+
+/// Rules for preparing a drawing for an external party
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedactionProfile {
+    /// Profile name
+    pub name: String,
+    /// Treatment of withheld layers
+    pub action: RedactionAction,
+    /// Layers withheld in addition to those flagged confidential
+    #[serde(default)]
+    pub layers: Vec<String>,
+    /// Clear author, company, subject, keywords, comments, custom
+    /// properties and document variables
+    pub strip_metadata: bool,
+    /// Clear custom entity attributes
+    pub strip_attributes: bool,
+    /// Resolution of rasterized content
+    pub raster_dpi: u32,
+}
+
+impl Default for RedactionProfile {
+    fn default() -> Self {
+        Self::new("External")
+    }
+}
+
+impl RedactionProfile {
+    /// Strip confidential layers and document metadata
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            action: RedactionAction::Strip,
+            layers: Vec::new(),
+            strip_metadata: true,
+            strip_attributes: false,
+            raster_dpi: 150,
+        }
+    }
+
+    /// Set the treatment of withheld layers
+    pub fn with_action(mut self, action: RedactionAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Withhold another layer by name
+    pub fn with_layer(mut self, layer: &str) -> Self {
+        self.layers.push(layer.to_string());
+        self
+    }
+
+    /// Whether entities on `layer` are withheld from `doc`
+    pub fn withholds(&self, doc: &Document, layer: &str) -> bool {
+        self.layers.iter().any(|l| l == layer)
+            || doc.get_layer(layer).is_some_and(|l| l.confidential)
+    }
+
+    /// Split `doc` into shared and withheld parts
+    pub fn redact(&self, doc: &Document) -> Redaction {
+        let mut shared = doc.clone();
+        let mut withheld = doc.clone();
+        let mut summary = RedactionSummary {
+            action: self.action,
+            ..RedactionSummary::default()
+        };
+
+        let withholds = |layer: &str| self.withholds(doc, layer);
+        let (hidden, kept): (Vec<_>, Vec<_>) =
+            shared.entities.drain(..).partition(|e| withholds(&e.layer));
+        summary.entities += hidden.len();
+        shared.entities = kept;
+        withheld.entities = hidden;
+
+        for block in shared.blocks.values_mut() {
+            let before = block.entities.len();
+            block.entities.retain(|e| !withholds(&e.layer));
+            summary.entities += before - block.entities.len();
+        }
+
+        let layers: BTreeSet<String> = doc
+            .layers
+            .keys()
+            .filter(|name| withholds(name))
+            .chain(self.layers.iter().filter(|name| doc.layers.contains_key(*name)))
+            .cloned()
+            .collect();
+        for name in &layers {
+            shared.layers.remove(name);
+        }
+        summary.layers = layers.into_iter().collect();
+
+        if self.strip_metadata {
+            let defaults = DocumentMetadata::default();
+            let metadata = &mut shared.metadata;
+            metadata.author = defaults.author;
+            metadata.company = defaults.company;
+            metadata.subject = defaults.subject;
+            metadata.keywords = defaults.keywords;
+            metadata.comments = defaults.comments;
+            metadata.custom_properties = defaults.custom_properties;
+            shared.variables.clear();
+        }
+        if self.strip_attributes {
+            let blocks = shared.blocks.values_mut().flat_map(|b| b.entities.iter_mut());
+            for entity in shared.entities.iter_mut().chain(blocks) {
+                entity.attributes.clear();
+            }
+        }
+
+        Redaction {
+            shared,
+            withheld,
+            summary,
+        }
+    }
+}
+
+/// A document split by a [`RedactionProfile`]
+#[derive(Debug, Clone)]
+pub struct Redaction {
+    /// Content that may leave the organization
+    pub shared: Document,
+    /// Top-level withheld entities, with the original layers and blocks so
+    /// they can be plotted for rasterizing; never written out as vectors
+    pub withheld: Document,
+    /// What was withheld
+    pub summary: RedactionSummary,
+}
+
+/// What a redaction removed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RedactionSummary {
+    /// Treatment requested by the profile
+    pub action: RedactionAction,
+    /// Withheld layers, sorted by name
+    pub layers: Vec<String>,
+    /// Entities removed, including those inside block definitions
+    pub entities: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::*;
+
+    fn line_on(layer: &str) -> Entity {
+        Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::new(0.0, 0.0, 0.0),
+                end: Vec3::new(10.0, 0.0, 0.0),
+            }),
+            layer.to_string(),
+        )
+    }
+
+    fn layer(name: &str, confidential: bool) -> Layer {
+        Layer {
+            name: name.to_string(),
+            color: Color::white(),
+            line_type: LineType::Continuous,
+            line_weight: LineWeight::Default,
+            visible: true,
+            locked: false,
+            frozen: false,
+            plottable: true,
+            confidential,
+        }
+    }
+
+    #[test]
+    fn test_redact_layers_blocks_and_metadata() {
+        let mut doc = Document::new();
+        doc.add_layer(layer("A-WALL", false));
+        doc.add_layer(layer("X-COST", true));
+        doc.add_layer(layer("X-NOTES", false));
+        doc.metadata.author = "J. Smith".to_string();
+        doc.variables.insert("CLIENT".to_string(), "Acme".to_string());
+
+        let mut wall = line_on("A-WALL");
+        wall.attributes.insert("cost".to_string(), "1200".to_string());
+        doc.add_entity(wall);
+        doc.add_entity(line_on("X-COST"));
+        doc.add_entity(line_on("X-NOTES"));
+        doc.add_block(Block {
+            name: "DOOR".to_string(),
+            base_point: Vec3::zero(),
+            entities: vec![line_on("A-WALL"), line_on("X-COST")],
+            description: String::new(),
+        });
+
+        let profile = RedactionProfile::new("Tender").with_layer("X-NOTES");
+        let redaction = profile.redact(&doc);
+        assert_eq!(redaction.summary.layers, vec!["X-COST", "X-NOTES"]);
+        assert_eq!(redaction.summary.entities, 3);
+        assert_eq!(redaction.summary.action, RedactionAction::Strip);
+
+        let shared = &redaction.shared;
+        assert_eq!(shared.entities.len(), 1);
+        assert_eq!(shared.blocks["DOOR"].entities.len(), 1);
+        assert!(shared.get_layer("X-COST").is_none());
+        assert!(shared.metadata.author.is_empty());
+        assert!(shared.variables.is_empty());
+        assert_eq!(shared.entities[0].attributes.len(), 1);
+        assert_eq!(redaction.withheld.entities.len(), 2);
+
+        let profile = RedactionProfile {
+            strip_metadata: false,
+            strip_attributes: true,
+            ..RedactionProfile::default()
+        };
+        let shared = profile.redact(&doc).shared;
+        assert_eq!(shared.entities.len(), 2);
+        assert_eq!(shared.metadata.author, "J. Smith");
+        assert!(shared.entities[0].attributes.is_empty());
+    }
+}
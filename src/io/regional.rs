@@ -0,0 +1,242 @@
+// CADDY - Enterprise CAD System
+// File I/O System - Regional Presets Module
+
+//! Regional presets
+//!
+//! Drafting conventions differ by region: most offices draw in millimeters
+//! on ISO A-series paper, North American ones in inches on ANSI Letter and
+//! Tabloid sheets, and German and Japanese ones dimension to DIN and JIS.
+//! A [`RegionalPreset`] bundles a region's default units, paper sizes,
+//! dimension standard and date format. It is picked at first run, from the
+//! system locale with [`Region::from_locale`] or by the user, or comes with
+//! a template, and is applied to a document's settings, where dimensioning,
+//! plotting, date fields and exports read it.
+//!
+//! ## Example
+//! ```
+//! use caddy::io::document::{Document, PaperSize};
+//! use caddy::io::regional::{DimensionStandard, Region};
+//! use caddy::io::units::Unit;
+//!
+//! let region = Region::from_locale("en_US.UTF-8");
+//! let doc = Document::for_region(region);
+//! assert_eq!(doc.settings.units, Unit::Inches);
+//! assert_eq!(doc.settings.paper_size, PaperSize::Letter);
+//! assert_eq!(doc.settings.regional.dimension_standard, DimensionStandard::Ansi);
+//! assert_eq!(doc.settings.regional.date_format, "%m/%d/%Y");
+//! ```
+
+use crate::io::document::{DocumentSettings, PaperSize};
+use crate::io::units::{PrecisionSettings, Unit};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Drafting region a preset is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Region {
+    /// ISO conventions in millimeters
+    #[default]
+    International,
+    /// ANSI conventions in inches
+    NorthAmerica,
+    /// ISO conventions with day-first dates
+    UnitedKingdom,
+    /// DIN conventions
+    Germany,
+    /// JIS conventions
+    Japan,
+}
+
+impl Region {
+    /// Every region, in the order offered to the user
+    pub const ALL: [Region; 5] = [
+        Region::International,
+        Region::NorthAmerica,
+        Region::UnitedKingdom,
+        Region::Germany,
+        Region::Japan,
+    ];
+
+    /// Name shown to the user
+    pub fn name(&self) -> &'static str {
+        match self {
+            Region::International => "International (ISO)",
+            Region::NorthAmerica => "North America (ANSI)",
+            Region::UnitedKingdom => "United Kingdom (ISO)",
+            Region::Germany => "Germany (DIN)",
+            Region::Japan => "Japan (JIS)",
+        }
+    }
+
+    /// Region for a locale such as `en-US`, `de_DE.UTF-8` or `ja`
+    ///
+    /// The country decides when there is one, otherwise the language;
+    /// anything unrecognized is [`Region::International`].
+    pub fn from_locale(locale: &str) -> Self {
+        let tag = locale.split(['.', '@']).next().unwrap_or_default();
+        let mut parts = tag.split(['-', '_']);
+        let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let country = parts.find(|part| part.len() == 2).map(str::to_ascii_uppercase);
+        match country.as_deref() {
+            Some("US" | "CA" | "PR") => Region::NorthAmerica,
+            Some("GB" | "IE") => Region::UnitedKingdom,
+            Some("DE" | "AT" | "CH") => Region::Germany,
+            Some("JP") => Region::Japan,
+            Some(_) => Region::International,
+            None => match language.as_str() {
+                "de" => Region::Germany,
+                "ja" => Region::Japan,
+                _ => Region::International,
+            },
+        }
+    }
+
+    /// The region's conventions
+    pub fn preset(&self) -> RegionalPreset {
+        let (units, paper_size, plot_paper) = match self {
+            Region::NorthAmerica => (Unit::Inches, PaperSize::Letter, PaperSize::Tabloid),
+            _ => (Unit::Millimeters, PaperSize::A4, PaperSize::A3),
+        };
+        let (dimension_standard, date_format) = match self {
+            Region::International => (DimensionStandard::Iso, "%Y-%m-%d"),
+            Region::NorthAmerica => (DimensionStandard::Ansi, "%m/%d/%Y"),
+            Region::UnitedKingdom => (DimensionStandard::Iso, "%d/%m/%Y"),
+            Region::Germany => (DimensionStandard::Din, "%d.%m.%Y"),
+            Region::Japan => (DimensionStandard::Jis, "%Y/%m/%d"),
+        };
+        RegionalPreset {
+            region: *self,
+            units,
+            paper_size,
+            plot_paper,
+            dimension_standard,
+            date_format: date_format.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Standard dimensions are drawn to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum DimensionStandard {
+    #[default]
+    Iso,
+    Ansi,
+    Din,
+    Jis,
+}
+
+impl DimensionStandard {
+    /// Name of the standard's dimension style
+    pub fn style_name(&self) -> &'static str {
+        match self {
+            DimensionStandard::Iso => "ISO-25",
+            DimensionStandard::Ansi => "ANSI",
+            DimensionStandard::Din => "DIN",
+            DimensionStandard::Jis => "JIS",
+        }
+    }
+}
+
+/// Regional conventions kept with a document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegionalSettings {
+    /// Region the document was set up for
+    pub region: Region,
+    /// Standard new dimensions follow
+    pub dimension_standard: DimensionStandard,
+    /// Paper plots default to
+    pub plot_paper: PaperSize,
+    /// `strftime` format date fields default to
+    pub date_format: String,
+}
+
+impl Default for RegionalSettings {
+    fn default() -> Self {
+        Region::default().preset().settings()
+    }
+}
+
+/// A region's default units, paper sizes, dimension standard and date format
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegionalPreset {
+    /// Region the preset is for
+    pub region: Region,
+    /// Drawing units
+    pub units: Unit,
+    /// Paper for the drawing and page exports
+    pub paper_size: PaperSize,
+    /// Paper for plots
+    pub plot_paper: PaperSize,
+    /// Standard new dimensions follow
+    pub dimension_standard: DimensionStandard,
+    /// `strftime` format for dates
+    pub date_format: String,
+}
+
+impl Default for RegionalPreset {
+    fn default() -> Self {
+        Region::default().preset()
+    }
+}
+
+impl RegionalPreset {
+    /// Set the units, precision, paper and regional settings of a document
+    pub fn apply(&self, settings: &mut DocumentSettings) {
+        settings.units = self.units;
+        settings.precision = PrecisionSettings::for_unit(self.units);
+        settings.paper_size = self.paper_size;
+        settings.regional = self.settings();
+    }
+
+    /// The part of the preset kept as the document's regional settings
+    pub fn settings(&self) -> RegionalSettings {
+        RegionalSettings {
+            region: self.region,
+            dimension_standard: self.dimension_standard,
+            plot_paper: self.plot_paper,
+            date_format: self.date_format.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::Document;
+
+    #[test]
+    fn test_region_from_locale() {
+        assert_eq!(Region::from_locale("en-US"), Region::NorthAmerica);
+        assert_eq!(Region::from_locale("fr_CA.UTF-8"), Region::NorthAmerica);
+        assert_eq!(Region::from_locale("en_GB"), Region::UnitedKingdom);
+        assert_eq!(Region::from_locale("de-CH"), Region::Germany);
+        assert_eq!(Region::from_locale("zh-Hant-TW"), Region::International);
+        assert_eq!(Region::from_locale("ja"), Region::Japan);
+        assert_eq!(Region::from_locale("C"), Region::International);
+        assert_eq!(Region::from_locale(""), Region::International);
+
+        // Applying a preset replaces the units, precision and paper
+        let mut settings = DocumentSettings::default();
+        Region::Germany.preset().apply(&mut settings);
+        assert_eq!(settings.units, Unit::Millimeters);
+        assert_eq!(settings.regional.dimension_standard, DimensionStandard::Din);
+        Region::NorthAmerica.preset().apply(&mut settings);
+        assert_eq!(settings.precision.decimal_places, Unit::Inches.default_precision());
+        assert_eq!(settings.regional.plot_paper, PaperSize::Tabloid);
+        assert_eq!(settings.regional.region, Region::NorthAmerica);
+
+        // Templates carry their region to new documents
+        let mut template = Document::for_region(Region::Japan);
+        template.metadata.title = "A3 title sheet".to_string();
+        let doc = Document::from_template(&template);
+        assert_ne!(doc.id, template.id);
+        assert_eq!(doc.metadata.title, Document::new().metadata.title);
+        assert_eq!(doc.settings.regional.dimension_standard, DimensionStandard::Jis);
+    }
+}
@@ -165,7 +165,7 @@ impl StepReader {
     /// Read a STEP file
     pub fn read_file<P: AsRef<Path>>(&self, path: P) -> StepResult<Document> {
         let file = File::open(path)?;
-        let _reader = BufReader::new(file);
+        let reader = BufReader::new(file);
         self.read(reader)
     }
 
@@ -174,10 +174,10 @@ impl StepReader {
         let mut parser = StepParser::new(reader);
 
         // Parse header section
-        let _header = parser.parse_header()?;
+        let header = parser.parse_header()?;
 
         // Parse data section
-        let _entities = parser.parse_data()?;
+        let entities = parser.parse_data()?;
 
         // Convert to document
         self.convert_to_document(header, entities)
@@ -496,7 +496,7 @@ impl<R: BufRead> StepParser<R> {
                 break;
             } else if in_data && trimmed.starts_with('#') {
                 // Parse entity
-                if let Some(entity) = self.parse_entity(trimmed)? {
+                if let Some(entity) = Self::parse_entity(trimmed, self.line_number)? {
                     entities.insert(entity.id, entity);
                 }
             }
@@ -505,12 +505,12 @@ impl<R: BufRead> StepParser<R> {
         Ok(entities)
     }
 
-    fn parse_entity(&self, line: &str) -> StepResult<Option<StepEntity>> {
+    fn parse_entity(line: &str, line_number: usize) -> StepResult<Option<StepEntity>> {
         // Simple entity parser
         if let Some(eq_pos) = line.find('=') {
             let id_str = &line[1..eq_pos].trim();
             let id = id_str.parse::<usize>().map_err(|e| StepError::Parse {
-                line: self.line_number,
+                line: line_number,
                 message: format!("Invalid entity ID: {}", e),
             })?;
 
@@ -549,7 +549,7 @@ mod tests {
 
     #[test]
     fn test_step_reader_creation() {
-        let _reader = StepReader::new();
+        let reader = StepReader::new();
         assert_eq!(reader.tolerance, 1e-6);
     }
 }
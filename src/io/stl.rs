@@ -20,6 +20,8 @@
 //! - Color support (binary STL extensions)
 //! - Multi-solid support
 
+use crate::geometry::mesh::{HalfEdgeMesh, MeshResult, TriangleFace, TriangleMesh, Vertex};
+use crate::geometry::repair::{MeshRepair, RepairReport};
 use crate::io::document::*;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
@@ -180,6 +182,63 @@ impl StlMesh {
         self.triangles.iter().map(|t| t.area()).sum()
     }
 
+    /// Indexed triangle mesh, with corners closer than `weld_tolerance`
+    /// merged into shared vertices
+    ///
+    /// Triangles that collapse when welded are dropped.
+    pub fn to_triangle_mesh(&self, weld_tolerance: f64) -> TriangleMesh {
+        let mut mesh = self.triangle_soup();
+        mesh.merge_vertices(weld_tolerance);
+        mesh.faces.retain(|f| {
+            let [a, b, c] = f.vertices;
+            a != b && b != c && c != a
+        });
+        mesh
+    }
+
+    /// Triangle mesh with three unshared vertices per triangle
+    fn triangle_soup(&self) -> TriangleMesh {
+        let vertices = self
+            .triangles
+            .iter()
+            .flat_map(|t| t.vertices.iter().map(|v| Vertex::new(v.to_point3())))
+            .collect();
+        let faces = (0..self.triangles.len())
+            .map(|i| TriangleFace::new(3 * i, 3 * i + 1, 3 * i + 2))
+            .collect();
+        TriangleMesh::from_data(vertices, faces)
+    }
+
+    /// Half-edge mesh of the welded triangles, for adjacency queries and
+    /// repair
+    pub fn to_half_edge_mesh(&self, weld_tolerance: f64) -> MeshResult<HalfEdgeMesh> {
+        HalfEdgeMesh::from_triangle_mesh(&self.to_triangle_mesh(weld_tolerance))
+    }
+
+    /// STL mesh of an indexed triangle mesh
+    pub fn from_triangle_mesh(name: String, mesh: &TriangleMesh) -> Self {
+        let corner = |i: usize| {
+            let p = mesh.vertices[i].position;
+            Vec3::new(p.x, p.y, p.z)
+        };
+        Self {
+            name,
+            triangles: mesh
+                .faces
+                .iter()
+                .map(|f| StlTriangle::new(corner(f.vertices[0]), corner(f.vertices[1]), corner(f.vertices[2])))
+                .collect(),
+        }
+    }
+
+    /// Weld, close and reorient the mesh for printing
+    pub fn repair(&mut self, repair: &MeshRepair) -> RepairReport {
+        let mut mesh = self.triangle_soup();
+        let report = repair.repair(&mut mesh);
+        self.triangles = Self::from_triangle_mesh(String::new(), &mesh).triangles;
+        report
+    }
+
     /// Validate mesh (check for degenerate triangles)
     pub fn validate(&self) -> Vec<String> {
         let mut errors = Vec::new();
@@ -230,7 +289,7 @@ impl StlReader {
                 std::env::current_dir().unwrap().join("temp")
             );
             let file = File::open(path_ref)?;
-            let _reader = BufReader::new(file);
+            let reader = BufReader::new(file);
             self.read_ascii(reader)
         } else {
             // Binary format
@@ -421,11 +480,11 @@ impl StlWriter {
     pub fn write_file<P: AsRef<Path>>(&self, mesh: &StlMesh, path: P) -> StlResult<()> {
         if self.binary_format {
             let file = File::create(path)?;
-            let _writer = BufWriter::new(file);
+            let writer = BufWriter::new(file);
             self.write_binary(mesh, writer)
         } else {
             let file = File::create(path)?;
-            let _writer = BufWriter::new(file);
+            let writer = BufWriter::new(file);
             self.write_ascii(mesh, writer)
         }
     }
@@ -466,7 +525,7 @@ impl StlWriter {
     /// Write binary STL format
     pub fn write_binary<W: Write>(&self, mesh: &StlMesh, mut writer: W) -> StlResult<()> {
         // Write 80-byte header
-        let _header = format!("Binary STL from CADDY: {}", mesh.name);
+        let header = format!("Binary STL from CADDY: {}", mesh.name);
         let mut header_bytes = [0u8; 80];
         let header_len = header.len().min(80);
         header_bytes[0..header_len].copy_from_slice(&header.as_bytes()[0..header_len]);
@@ -543,4 +602,53 @@ mod tests {
         assert_eq!(min.x, 0.0);
         assert_eq!(max.x, 1.0);
     }
+
+    #[test]
+    fn test_weld_into_half_edge_mesh() {
+        // Two facets of a square, as separate triangles the way STL stores them
+        let mut mesh = StlMesh::new("Square".to_string());
+        let p = |x: f64, y: f64| Vec3::new(x, y, 0.0);
+        mesh.triangles.push(StlTriangle::new(p(0.0, 0.0), p(1.0, 0.0), p(1.0, 1.0)));
+        mesh.triangles.push(StlTriangle::new(p(0.0, 0.0), p(1.0, 1.0 + 1e-9), p(0.0, 1.0)));
+        // A sliver that collapses when welded
+        mesh.triangles.push(StlTriangle::new(p(0.0, 0.0), p(1e-9, 0.0), p(0.0, 1.0)));
+
+        let triangles = mesh.to_triangle_mesh(1e-6);
+        assert_eq!(triangles.vertices.len(), 4);
+        assert_eq!(triangles.faces.len(), 2);
+
+        let topology = mesh.to_half_edge_mesh(1e-6).unwrap();
+        assert_eq!(topology.edge_count(), 5);
+        assert_eq!(topology.boundary_loops().len(), 1);
+    }
+
+    #[test]
+    fn test_repair_closes_mesh() {
+        // Tetrahedron missing one facet, with one facet wound inward
+        let mut mesh = StlMesh::new("Tetra".to_string());
+        let [o, x, y, z] = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ];
+        mesh.triangles.push(StlTriangle::new(o, y, x));
+        mesh.triangles.push(StlTriangle::new(o, z, y));
+        mesh.triangles.push(StlTriangle::new(o, z, x));
+
+        let report = mesh.repair(&MeshRepair::new());
+        assert_eq!(report.merged_vertices, 5);
+        assert_eq!(report.flipped_faces, 1);
+        assert_eq!(report.filled_holes, 1);
+        assert!(report.after.is_watertight());
+        assert_eq!(mesh.triangles.len(), 4);
+        assert!((mesh.volume() - 1.0 / 6.0).abs() < 1e-12);
+        // Facet normals point away from the solid's centroid
+        for t in &mesh.triangles {
+            let away = (0..3).map(|k| t.vertices[k]).fold(0.0, |sum, v| {
+                sum + t.normal.x * (v.x - 0.25) + t.normal.y * (v.y - 0.25) + t.normal.z * (v.z - 0.25)
+            });
+            assert!(away > 0.0);
+        }
+    }
 }
@@ -0,0 +1,412 @@
+// CADDY - Enterprise CAD System
+// File I/O System - Entity Tags Module
+
+//! Entity tags, classifications and saved queries
+//!
+//! Entities carry any number of free-form tags (`fire-rated`, `phase-2`)
+//! and at most one hierarchical classification (`Structural/Steel/Beam`).
+//! Both are stored in the entity attributes, under [`TAGS_ATTRIBUTE`] and
+//! the export pipeline's [`CLASSIFICATION_ATTRIBUTE`], so native files keep
+//! them as they are. DXF files keep them as extended entity data registered
+//! to [`XDATA_APP`].
+//!
+//! An [`EntityQuery`] picks entities by tag, classification branch, layer
+//! and type. Queries are saved by name in [`Document::queries`] and drive
+//! selection ([`EntityQuery::select`]), visibility ([`hide`], [`isolate`])
+//! and, through matching fields, export selectors and takeoff filters.
+
+use crate::io::document::{Document, Entity};
+use crate::io::pipeline::CLASSIFICATION_ATTRIBUTE;
+use crate::standards::matches_pattern;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Entity attribute holding its tags, separated by [`TAG_SEPARATOR`]
+pub const TAGS_ATTRIBUTE: &str = "TAGS";
+
+/// Separator between stored tags
+pub const TAG_SEPARATOR: char = ';';
+
+/// Separator between classification levels
+pub const CLASSIFICATION_SEPARATOR: char = '/';
+
+/// Application name of CADDY extended entity data in DXF files
+pub const XDATA_APP: &str = "CADDY";
+
+const XDATA_TAG: &str = "TAG:";
+const XDATA_CLASSIFICATION: &str = "CLASSIFICATION:";
+
+/// Tag errors
+#[derive(Error, Debug)]
+pub enum TagError {
+    #[error("Invalid tag '{0}'")]
+    InvalidTag(String),
+    #[error("Invalid classification '{0}'")]
+    InvalidClassification(String),
+    #[error("No saved query named '{0}'")]
+    UnknownQuery(String),
+}
+
+pub type TagResult<T> = Result<T, TagError>;
+
+/// An entity's tags, in stored order
+pub fn tags(entity: &Entity) -> Vec<String> {
+    entity
+        .attributes
+        .get(TAGS_ATTRIBUTE)
+        .map(|stored| {
+            stored
+                .split(TAG_SEPARATOR)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether an entity has a tag (ignoring case)
+pub fn has_tag(entity: &Entity, tag: &str) -> bool {
+    tags(entity).iter().any(|t| t.eq_ignore_ascii_case(tag.trim()))
+}
+
+/// Tag an entity; returns whether the tag is new
+///
+/// Tags are trimmed and may not be empty or contain [`TAG_SEPARATOR`].
+pub fn add_tag(entity: &mut Entity, tag: &str) -> TagResult<bool> {
+    let tag = tag.trim();
+    if tag.is_empty() || tag.contains(TAG_SEPARATOR) {
+        return Err(TagError::InvalidTag(tag.to_string()));
+    }
+    if has_tag(entity, tag) {
+        return Ok(false);
+    }
+    let mut all = tags(entity);
+    all.push(tag.to_string());
+    store_tags(entity, &all);
+    Ok(true)
+}
+
+/// Remove a tag (ignoring case); returns whether it was present
+pub fn remove_tag(entity: &mut Entity, tag: &str) -> bool {
+    let mut all = tags(entity);
+    let before = all.len();
+    all.retain(|t| !t.eq_ignore_ascii_case(tag.trim()));
+    if all.len() == before {
+        return false;
+    }
+    store_tags(entity, &all);
+    true
+}
+
+fn store_tags(entity: &mut Entity, tags: &[String]) {
+    if tags.is_empty() {
+        entity.attributes.remove(TAGS_ATTRIBUTE);
+    } else {
+        let stored = tags.join(&TAG_SEPARATOR.to_string());
+        entity.attributes.insert(TAGS_ATTRIBUTE.to_string(), stored);
+    }
+}
+
+/// An entity's classification path
+pub fn classification(entity: &Entity) -> Option<&str> {
+    entity.attributes.get(CLASSIFICATION_ATTRIBUTE).map(String::as_str)
+}
+
+/// Classify an entity, or clear its classification with `None`
+///
+/// Levels are trimmed; a path with an empty level is rejected.
+pub fn set_classification(entity: &mut Entity, path: Option<&str>) -> TagResult<()> {
+    let Some(path) = path else {
+        entity.attributes.remove(CLASSIFICATION_ATTRIBUTE);
+        return Ok(());
+    };
+    let levels: Vec<&str> = path.split(CLASSIFICATION_SEPARATOR).map(str::trim).collect();
+    if levels.iter().any(|level| level.is_empty()) {
+        return Err(TagError::InvalidClassification(path.to_string()));
+    }
+    let normalized = levels.join(&CLASSIFICATION_SEPARATOR.to_string());
+    entity.attributes.insert(CLASSIFICATION_ATTRIBUTE.to_string(), normalized);
+    Ok(())
+}
+
+/// Whether an entity is classified as `branch` or anything below it
+///
+/// `Structural` covers `Structural/Steel/Beam` but not `StructuralGlass`.
+/// Levels may use `*` and `?` wildcards and ignore case.
+pub fn is_classified_under(entity: &Entity, branch: &str) -> bool {
+    let Some(path) = classification(entity) else {
+        return false;
+    };
+    let mut levels = path.split(CLASSIFICATION_SEPARATOR);
+    branch
+        .split(CLASSIFICATION_SEPARATOR)
+        .all(|pattern| levels.next().is_some_and(|level| matches_pattern(pattern.trim(), level)))
+}
+
+/// Classification truncated to its first `depth` levels, for grouping
+pub fn classification_level(entity: &Entity, depth: usize) -> Option<String> {
+    let levels: Vec<&str> = classification(entity)?
+        .split(CLASSIFICATION_SEPARATOR)
+        .take(depth.max(1))
+        .collect();
+    Some(levels.join(&CLASSIFICATION_SEPARATOR.to_string()))
+}
+
+/// Number of entities carrying each tag, by tag
+pub fn tag_counts(doc: &Document) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for entity in &doc.entities {
+        for tag in tags(entity) {
+            *counts.entry(tag).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Which entities a query picks
+///
+/// Tag, layer and type entries use `*` and `?` wildcards and ignore case.
+/// Every non-empty list must be satisfied; an empty query picks everything.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntityQuery {
+    /// Tags that must all be present
+    #[serde(default)]
+    pub all_tags: Vec<String>,
+    /// Tags of which at least one must be present
+    #[serde(default)]
+    pub any_tags: Vec<String>,
+    /// Tags that must be absent
+    #[serde(default)]
+    pub without_tags: Vec<String>,
+    /// Classification branches, any of which the entity must be under
+    #[serde(default)]
+    pub classifications: Vec<String>,
+    /// Layer name patterns
+    #[serde(default)]
+    pub layers: Vec<String>,
+    /// Entity type patterns (`Line`, `Insert`, ...)
+    #[serde(default)]
+    pub kinds: Vec<String>,
+}
+
+impl EntityQuery {
+    /// Query picking every entity
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also require a tag matching `pattern`
+    pub fn tagged(mut self, pattern: &str) -> Self {
+        self.all_tags.push(pattern.to_string());
+        self
+    }
+
+    /// Also accept a tag matching `pattern` among the alternatives
+    pub fn tagged_any(mut self, pattern: &str) -> Self {
+        self.any_tags.push(pattern.to_string());
+        self
+    }
+
+    /// Leave out entities with a tag matching `pattern`
+    pub fn not_tagged(mut self, pattern: &str) -> Self {
+        self.without_tags.push(pattern.to_string());
+        self
+    }
+
+    /// Also accept entities classified under `branch`
+    pub fn classified(mut self, branch: &str) -> Self {
+        self.classifications.push(branch.to_string());
+        self
+    }
+
+    /// Also accept layers matching `pattern`
+    pub fn on_layer(mut self, pattern: &str) -> Self {
+        self.layers.push(pattern.to_string());
+        self
+    }
+
+    /// Also accept entity types matching `pattern`
+    pub fn of_kind(mut self, pattern: &str) -> Self {
+        self.kinds.push(pattern.to_string());
+        self
+    }
+
+    /// Whether `entity` is picked
+    pub fn matches(&self, entity: &Entity) -> bool {
+        let tags = tags(entity);
+        let tagged = |pattern: &String| tags.iter().any(|t| matches_pattern(pattern, t));
+        let any = |patterns: &[String], name: &str| {
+            patterns.is_empty() || patterns.iter().any(|p| matches_pattern(p, name))
+        };
+
+        self.all_tags.iter().all(tagged)
+            && (self.any_tags.is_empty() || self.any_tags.iter().any(tagged))
+            && !self.without_tags.iter().any(tagged)
+            && (self.classifications.is_empty()
+                || self.classifications.iter().any(|b| is_classified_under(entity, b)))
+            && any(&self.layers, &entity.layer)
+            && any(&self.kinds, entity.geometry.type_name())
+    }
+
+    /// Ids of the picked entities, in drawing order
+    pub fn select(&self, doc: &Document) -> Vec<Uuid> {
+        doc.entities
+            .iter()
+            .filter(|e| self.matches(e))
+            .map(|e| e.id)
+            .collect()
+    }
+}
+
+/// Run a query saved in the document
+pub fn select_saved(doc: &Document, name: &str) -> TagResult<Vec<Uuid>> {
+    doc.queries
+        .get(name)
+        .map(|query| query.select(doc))
+        .ok_or_else(|| TagError::UnknownQuery(name.to_string()))
+}
+
+/// Hide the visible entities a query picks; returns the ones hidden, for
+/// [`show`]
+pub fn hide(doc: &mut Document, query: &EntityQuery) -> Vec<Uuid> {
+    set_hidden(doc, |entity| query.matches(entity))
+}
+
+/// Hide every visible entity a query does not pick; returns the ones
+/// hidden, for [`show`]
+pub fn isolate(doc: &mut Document, query: &EntityQuery) -> Vec<Uuid> {
+    set_hidden(doc, |entity| !query.matches(entity))
+}
+
+/// Make entities visible again
+pub fn show(doc: &mut Document, ids: &[Uuid]) {
+    for entity in doc.entities.iter_mut().filter(|e| ids.contains(&e.id)) {
+        entity.visible = true;
+    }
+}
+
+fn set_hidden(doc: &mut Document, hidden: impl Fn(&Entity) -> bool) -> Vec<Uuid> {
+    let mut changed = Vec::new();
+    for entity in doc.entities.iter_mut() {
+        if entity.visible && hidden(entity) {
+            entity.visible = false;
+            changed.push(entity.id);
+        }
+    }
+    changed
+}
+
+/// Extended data strings (DXF group 1000) recording an entity's tags and
+/// classification
+pub(crate) fn to_xdata(entity: &Entity) -> Vec<String> {
+    let mut values: Vec<String> = tags(entity)
+        .into_iter()
+        .map(|tag| format!("{}{}", XDATA_TAG, tag))
+        .collect();
+    if let Some(path) = classification(entity) {
+        values.push(format!("{}{}", XDATA_CLASSIFICATION, path));
+    }
+    values
+}
+
+/// Restore tags and classification from [`XDATA_APP`] extended data strings;
+/// unknown strings are ignored
+pub(crate) fn apply_xdata(entity: &mut Entity, values: &[String]) {
+    for value in values {
+        if let Some(tag) = value.strip_prefix(XDATA_TAG) {
+            let _ = add_tag(entity, tag);
+        } else if let Some(path) = value.strip_prefix(XDATA_CLASSIFICATION) {
+            let _ = set_classification(entity, Some(path));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{GeometryType, Line, Vec3};
+
+    fn line(layer: &str, tags: &[&str], class: Option<&str>) -> Entity {
+        let mut entity = Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::new(0.0, 0.0, 0.0),
+                end: Vec3::new(1.0, 0.0, 0.0),
+            }),
+            layer.to_string(),
+        );
+        for tag in tags {
+            add_tag(&mut entity, tag).unwrap();
+        }
+        set_classification(&mut entity, class).unwrap();
+        entity
+    }
+
+    #[test]
+    fn test_tags_and_classification() {
+        let mut wall = line("A-WALL", &["fire-rated", " phase-2 "], Some("Structural / Masonry"));
+        assert_eq!(tags(&wall), vec!["fire-rated", "phase-2"]);
+        assert!(!add_tag(&mut wall, "Fire-Rated").unwrap());
+        assert!(matches!(add_tag(&mut wall, "a;b"), Err(TagError::InvalidTag(_))));
+        assert!(remove_tag(&mut wall, "PHASE-2"));
+        assert!(!remove_tag(&mut wall, "phase-2"));
+
+        assert_eq!(classification(&wall), Some("Structural/Masonry"));
+        assert!(is_classified_under(&wall, "structural"));
+        assert!(is_classified_under(&wall, "Structural/*"));
+        assert!(!is_classified_under(&wall, "Structural/Masonry/Block"));
+        assert!(!is_classified_under(&line("0", &[], Some("StructuralGlass")), "Structural"));
+        assert_eq!(classification_level(&wall, 1).as_deref(), Some("Structural"));
+        assert!(set_classification(&mut wall, Some("Structural//Steel")).is_err());
+        set_classification(&mut wall, None).unwrap();
+        assert_eq!(classification(&wall), None);
+
+        assert!(remove_tag(&mut wall, "fire-rated"));
+        assert!(!wall.attributes.contains_key(TAGS_ATTRIBUTE));
+    }
+
+    #[test]
+    fn test_queries_and_visibility() {
+        let mut doc = Document::new();
+        let a = doc.add_entity(line("A-WALL", &["fire-rated", "phase-1"], Some("Structural/Masonry")));
+        let b = doc.add_entity(line("A-WALL", &["phase-2"], Some("Structural/Steel")));
+        let c = doc.add_entity(line("A-DOOR", &["fire-rated", "phase-2"], Some("Openings")));
+
+        assert_eq!(EntityQuery::new().tagged("fire-rated").select(&doc), vec![a, c]);
+        assert_eq!(EntityQuery::new().tagged("phase-*").not_tagged("fire*").select(&doc), vec![b]);
+        assert_eq!(EntityQuery::new().classified("Structural").on_layer("a-*").select(&doc), vec![a, b]);
+        assert_eq!(
+            EntityQuery::new().tagged_any("phase-1").tagged_any("phase-2").of_kind("Line").select(&doc),
+            vec![a, b, c]
+        );
+
+        doc.queries.insert("Fire".to_string(), EntityQuery::new().tagged("fire-rated"));
+        assert_eq!(select_saved(&doc, "Fire").unwrap(), vec![a, c]);
+        assert!(matches!(select_saved(&doc, "Nope"), Err(TagError::UnknownQuery(_))));
+
+        let fire = doc.queries["Fire"].clone();
+        let hidden = isolate(&mut doc, &fire);
+        assert_eq!(hidden, vec![b]);
+        assert_eq!(hide(&mut doc, &EntityQuery::new().on_layer("A-DOOR")), vec![c]);
+        show(&mut doc, &hidden);
+        let visible: Vec<Uuid> = doc.entities.iter().filter(|e| e.visible).map(|e| e.id).collect();
+        assert_eq!(visible, vec![a, b]);
+
+        let counts = tag_counts(&doc);
+        assert_eq!(counts["fire-rated"], 2);
+        assert_eq!(counts["phase-2"], 2);
+    }
+
+    #[test]
+    fn test_xdata_roundtrip() {
+        let source = line("0", &["fire-rated", "phase-2"], Some("Structural/Steel"));
+        let values = to_xdata(&source);
+        assert_eq!(values, vec!["TAG:fire-rated", "TAG:phase-2", "CLASSIFICATION:Structural/Steel"]);
+
+        let mut restored = line("0", &[], None);
+        apply_xdata(&mut restored, &values);
+        assert_eq!(tags(&restored), tags(&source));
+        assert_eq!(classification(&restored), Some("Structural/Steel"));
+    }
+}
@@ -0,0 +1,829 @@
+// CADDY - Enterprise CAD System
+// File I/O System - 3MF Format Support
+
+//! # 3MF (3D Manufacturing Format) Support
+//!
+//! Provides read/write support for 3MF packages, the recommended format for
+//! 3D printing exports. Unlike STL, a 3MF file records the unit its
+//! coordinates are in, shares vertices between triangles, and carries colors
+//! and the placement of each part on the build plate.
+//!
+//! A package is a ZIP archive holding an XML model (`3D/3dmodel.model`),
+//! found through the package relationships, and the content types of its
+//! parts.
+//!
+//! ## Features
+//!
+//! - Indexed triangle meshes, one per object
+//! - Model units from micron to meter
+//! - Base materials with display colors, per object or per triangle
+//! - Build items placing objects with affine transforms
+//!
+//! ## Example
+//! ```no_run
+//! use caddy::io::threemf::{ThreeMfItem, ThreeMfModel, ThreeMfObject, ThreeMfWriter};
+//! use caddy::io::document::{Color, Vec3};
+//! use caddy::io::units::Unit;
+//!
+//! let mut model = ThreeMfModel::new(Unit::Millimeters.into());
+//! let red = model.add_material("Red PLA", Color::red());
+//! let mut part = ThreeMfObject::new("Wedge");
+//! part.vertices = vec![
+//!     Vec3::new(0.0, 0.0, 0.0),
+//!     Vec3::new(10.0, 0.0, 0.0),
+//!     Vec3::new(0.0, 10.0, 0.0),
+//!     Vec3::new(0.0, 0.0, 10.0),
+//! ];
+//! part.add_triangle([0, 2, 1]);
+//! part.add_triangle([0, 1, 3]);
+//! part.add_triangle([1, 2, 3]);
+//! part.add_triangle([0, 3, 2]);
+//! part.material = Some(red);
+//! let id = model.add_object(part);
+//! model.items.push(ThreeMfItem::new(id).translated(50.0, 50.0, 0.0));
+//!
+//! ThreeMfWriter::new().write_file(&model, "wedge.3mf").unwrap();
+//! ```
+
+use crate::geometry::mesh::{TriangleFace, TriangleMesh, Vertex};
+use crate::io::document::{Color, Vec3};
+use crate::io::units::Unit;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, Write};
+use std::path::Path;
+use thiserror::Error;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Core specification namespace of the model part
+pub const CORE_NAMESPACE: &str = "http://schemas.microsoft.com/3dmanufacturing/core/2015/02";
+/// Package path of the model part written by [`ThreeMfWriter`]
+pub const MODEL_PATH: &str = "3D/3dmodel.model";
+
+const CONTENT_TYPES_PATH: &str = "[Content_Types].xml";
+const RELATIONSHIPS_PATH: &str = "_rels/.rels";
+const MODEL_RELATIONSHIP: &str = "http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel";
+
+/// 3MF-related errors
+#[derive(Error, Debug)]
+pub enum ThreeMfError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Archive error: {0}")]
+    Archive(String),
+
+    #[error("XML error: {0}")]
+    Xml(String),
+
+    #[error("Invalid 3MF file: {0}")]
+    InvalidFile(String),
+}
+
+impl From<zip::result::ZipError> for ThreeMfError {
+    fn from(e: zip::result::ZipError) -> Self {
+        ThreeMfError::Archive(e.to_string())
+    }
+}
+
+impl From<quick_xml::Error> for ThreeMfError {
+    fn from(e: quick_xml::Error) -> Self {
+        ThreeMfError::Xml(e.to_string())
+    }
+}
+
+pub type ThreeMfResult<T> = Result<T, ThreeMfError>;
+
+/// Unit the coordinates of a model are in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThreeMfUnit {
+    Micron,
+    #[default]
+    Millimeter,
+    Centimeter,
+    Inch,
+    Foot,
+    Meter,
+}
+
+impl ThreeMfUnit {
+    /// Name used in the `unit` attribute
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThreeMfUnit::Micron => "micron",
+            ThreeMfUnit::Millimeter => "millimeter",
+            ThreeMfUnit::Centimeter => "centimeter",
+            ThreeMfUnit::Inch => "inch",
+            ThreeMfUnit::Foot => "foot",
+            ThreeMfUnit::Meter => "meter",
+        }
+    }
+
+    /// Unit named by a `unit` attribute
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "micron" => Some(ThreeMfUnit::Micron),
+            "millimeter" => Some(ThreeMfUnit::Millimeter),
+            "centimeter" => Some(ThreeMfUnit::Centimeter),
+            "inch" => Some(ThreeMfUnit::Inch),
+            "foot" => Some(ThreeMfUnit::Foot),
+            "meter" => Some(ThreeMfUnit::Meter),
+            _ => None,
+        }
+    }
+
+    /// Length of one unit in meters
+    pub fn to_meters(&self) -> f64 {
+        match self {
+            ThreeMfUnit::Micron => 1e-6,
+            ThreeMfUnit::Millimeter => 0.001,
+            ThreeMfUnit::Centimeter => 0.01,
+            ThreeMfUnit::Inch => 0.0254,
+            ThreeMfUnit::Foot => 0.3048,
+            ThreeMfUnit::Meter => 1.0,
+        }
+    }
+}
+
+impl From<Unit> for ThreeMfUnit {
+    fn from(unit: Unit) -> Self {
+        match unit {
+            Unit::Millimeters => ThreeMfUnit::Millimeter,
+            Unit::Centimeters => ThreeMfUnit::Centimeter,
+            Unit::Meters | Unit::Decimal => ThreeMfUnit::Meter,
+            Unit::Inches | Unit::Fractional => ThreeMfUnit::Inch,
+            Unit::Feet | Unit::Engineering | Unit::Architectural => ThreeMfUnit::Foot,
+        }
+    }
+}
+
+/// A base material with its display color
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreeMfMaterial {
+    pub name: String,
+    pub color: Color,
+}
+
+/// Triangle of an object's mesh
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThreeMfTriangle {
+    /// Indices into the object's vertices, counter-clockwise seen from outside
+    pub vertices: [usize; 3],
+    /// Material overriding the object's, as an index into the model's materials
+    pub material: Option<usize>,
+}
+
+/// A mesh object
+#[derive(Debug, Clone)]
+pub struct ThreeMfObject {
+    /// Resource id, unique among the model's objects and material groups
+    pub id: u32,
+    pub name: String,
+    pub vertices: Vec<Vec3>,
+    pub triangles: Vec<ThreeMfTriangle>,
+    /// Material of the object, as an index into the model's materials
+    pub material: Option<usize>,
+}
+
+impl ThreeMfObject {
+    /// Empty object; its id is assigned by [`ThreeMfModel::add_object`]
+    pub fn new(name: &str) -> Self {
+        Self {
+            id: 0,
+            name: name.to_string(),
+            vertices: Vec::new(),
+            triangles: Vec::new(),
+            material: None,
+        }
+    }
+
+    /// Add a triangle in the object's material
+    pub fn add_triangle(&mut self, vertices: [usize; 3]) {
+        self.triangles.push(ThreeMfTriangle { vertices, material: None });
+    }
+
+    /// Object of an indexed triangle mesh
+    pub fn from_triangle_mesh(name: &str, mesh: &TriangleMesh) -> Self {
+        let mut object = Self::new(name);
+        object.vertices = mesh
+            .vertices
+            .iter()
+            .map(|v| Vec3::new(v.position.x, v.position.y, v.position.z))
+            .collect();
+        for face in &mesh.faces {
+            object.add_triangle(face.vertices);
+        }
+        object
+    }
+
+    /// Indexed triangle mesh of the object, for repair and 3D operations
+    pub fn to_triangle_mesh(&self) -> TriangleMesh {
+        let vertices = self.vertices.iter().map(|v| Vertex::new(v.to_point3())).collect();
+        let faces = self
+            .triangles
+            .iter()
+            .map(|t| TriangleFace::new(t.vertices[0], t.vertices[1], t.vertices[2]))
+            .collect();
+        TriangleMesh::from_data(vertices, faces)
+    }
+}
+
+/// Placement of an object on the build plate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThreeMfItem {
+    pub object_id: u32,
+    /// Affine transform `m00 m01 m02 m10 m11 m12 m20 m21 m22 m30 m31 m32`,
+    /// applied to row vectors, with the translation last
+    pub transform: [f64; 12],
+}
+
+impl ThreeMfItem {
+    /// Transform leaving an object where it is
+    pub const IDENTITY: [f64; 12] = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0];
+
+    /// Item placing an object as modeled
+    pub fn new(object_id: u32) -> Self {
+        Self {
+            object_id,
+            transform: Self::IDENTITY,
+        }
+    }
+
+    /// The item moved by a translation
+    pub fn translated(mut self, x: f64, y: f64, z: f64) -> Self {
+        self.transform[9] += x;
+        self.transform[10] += y;
+        self.transform[11] += z;
+        self
+    }
+
+    /// Where the item puts a point of its object
+    pub fn apply(&self, p: Vec3) -> Vec3 {
+        let m = &self.transform;
+        Vec3::new(
+            p.x * m[0] + p.y * m[3] + p.z * m[6] + m[9],
+            p.x * m[1] + p.y * m[4] + p.z * m[7] + m[10],
+            p.x * m[2] + p.y * m[5] + p.z * m[8] + m[11],
+        )
+    }
+}
+
+/// A 3MF model: objects, their materials and the build
+#[derive(Debug, Clone, Default)]
+pub struct ThreeMfModel {
+    pub unit: ThreeMfUnit,
+    pub materials: Vec<ThreeMfMaterial>,
+    pub objects: Vec<ThreeMfObject>,
+    /// Objects to print and where; objects not listed are not printed
+    pub items: Vec<ThreeMfItem>,
+}
+
+impl ThreeMfModel {
+    /// Create a new empty model
+    pub fn new(unit: ThreeMfUnit) -> Self {
+        Self {
+            unit,
+            ..Self::default()
+        }
+    }
+
+    /// Add a material, returning its index
+    pub fn add_material(&mut self, name: &str, color: Color) -> usize {
+        self.materials.push(ThreeMfMaterial {
+            name: name.to_string(),
+            color,
+        });
+        self.materials.len() - 1
+    }
+
+    /// Add an object under a fresh id, returning the id
+    pub fn add_object(&mut self, mut object: ThreeMfObject) -> u32 {
+        object.id = self.objects.iter().map(|o| o.id).max().unwrap_or(0) + 1;
+        let id = object.id;
+        self.objects.push(object);
+        id
+    }
+
+    /// Object with a resource id
+    pub fn object(&self, id: u32) -> Option<&ThreeMfObject> {
+        self.objects.iter().find(|o| o.id == id)
+    }
+
+    /// Total number of triangles over all objects
+    pub fn triangle_count(&self) -> usize {
+        self.objects.iter().map(|o| o.triangles.len()).sum()
+    }
+
+    /// Rescale coordinates and translations into a drawing unit
+    pub fn convert_to(&mut self, unit: Unit) {
+        let target = ThreeMfUnit::from(unit);
+        let scale = self.unit.to_meters() / target.to_meters();
+        for object in &mut self.objects {
+            for v in &mut object.vertices {
+                *v = Vec3::new(v.x * scale, v.y * scale, v.z * scale);
+            }
+        }
+        for item in &mut self.items {
+            for t in &mut item.transform[9..] {
+                *t *= scale;
+            }
+        }
+        self.unit = target;
+    }
+
+    /// Check indices and references, which the reader relies on
+    pub fn validate(&self) -> ThreeMfResult<()> {
+        let invalid = |message: String| Err(ThreeMfError::InvalidFile(message));
+        for object in &self.objects {
+            if object.material.is_some_and(|m| m >= self.materials.len()) {
+                return invalid(format!("Object {} has no such material", object.id));
+            }
+            for (i, triangle) in object.triangles.iter().enumerate() {
+                if triangle.vertices.iter().any(|&v| v >= object.vertices.len()) {
+                    return invalid(format!("Triangle {} of object {} has no such vertex", i, object.id));
+                }
+                if triangle.material.is_some_and(|m| m >= self.materials.len()) {
+                    return invalid(format!("Triangle {} of object {} has no such material", i, object.id));
+                }
+            }
+        }
+        for item in &self.items {
+            if self.object(item.object_id).is_none() {
+                return invalid(format!("Build item refers to missing object {}", item.object_id));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 3MF file reader
+pub struct ThreeMfReader {
+    validate_model: bool,
+}
+
+impl ThreeMfReader {
+    /// Create a new 3MF reader
+    pub fn new() -> Self {
+        Self { validate_model: true }
+    }
+
+    /// Skip index and reference validation
+    pub fn skip_validation(mut self) -> Self {
+        self.validate_model = false;
+        self
+    }
+
+    /// Read 3MF package from file
+    pub fn read_file<P: AsRef<Path>>(&self, path: P) -> ThreeMfResult<ThreeMfModel> {
+        self.read(BufReader::new(File::open(path)?))
+    }
+
+    /// Read 3MF package from a seekable reader
+    pub fn read<R: Read + Seek>(&self, reader: R) -> ThreeMfResult<ThreeMfModel> {
+        let mut zip = ZipArchive::new(reader)?;
+        let model_path = match zip.by_name(RELATIONSHIPS_PATH) {
+            Ok(mut entry) => {
+                let mut rels = String::new();
+                entry.read_to_string(&mut rels)?;
+                model_target(&rels)?
+            }
+            Err(_) => None,
+        };
+        let model_path = model_path.unwrap_or_else(|| MODEL_PATH.to_string());
+        let mut xml = String::new();
+        zip.by_name(&model_path)?.read_to_string(&mut xml)?;
+        self.read_model(&xml)
+    }
+
+    /// Parse the XML of a model part
+    pub fn read_model(&self, xml: &str) -> ThreeMfResult<ThreeMfModel> {
+        let mut model = ThreeMfModel::default();
+        // Material groups by resource id, as indices into `model.materials`
+        let mut groups: HashMap<u32, Vec<usize>> = HashMap::new();
+        let mut group = None;
+        // Object properties, resolved once every group is known
+        let mut object_property: Vec<Option<(u32, usize)>> = Vec::new();
+        let mut triangle_properties: Vec<Vec<Option<(u32, usize)>>> = Vec::new();
+
+        let mut reader = Reader::from_str(xml);
+        loop {
+            let (e, empty) = match reader.read_event()? {
+                Event::Start(e) => (e, false),
+                Event::Empty(e) => (e, true),
+                Event::End(e) => {
+                    if e.local_name().as_ref() == b"basematerials" {
+                        group = None;
+                    }
+                    continue;
+                }
+                Event::Eof => break,
+                _ => continue,
+            };
+            let attrs = attributes(&e)?;
+            let number = |key: &str| -> ThreeMfResult<Option<f64>> {
+                attrs.get(key).map(|value| parse_number(key, value)).transpose()
+            };
+            let index = |key: &str| -> ThreeMfResult<Option<usize>> {
+                Ok(number(key)?.map(|n| n as usize))
+            };
+            match e.local_name().as_ref() {
+                b"model" => {
+                    let unit = attrs.get("unit").map(String::as_str).unwrap_or("millimeter");
+                    model.unit = ThreeMfUnit::parse(unit)
+                        .ok_or_else(|| ThreeMfError::InvalidFile(format!("Unknown unit: {}", unit)))?;
+                }
+                b"basematerials" => {
+                    let id = required(&attrs, "id", index("id")?)? as u32;
+                    groups.insert(id, Vec::new());
+                    group = (!empty).then_some(id);
+                }
+                b"base" => {
+                    if let Some(id) = group {
+                        let color = match attrs.get("displaycolor") {
+                            Some(color) => parse_color(color)?,
+                            None => Color::white(),
+                        };
+                        let name = attrs.get("name").cloned().unwrap_or_default();
+                        let index = model.add_material(&name, color);
+                        groups.entry(id).or_default().push(index);
+                    }
+                }
+                b"object" => {
+                    if attrs.get("type").is_some_and(|t| t != "model") {
+                        log::warn!("3MF: treating {} object as a model", attrs["type"]);
+                    }
+                    let mut object = ThreeMfObject::new(attrs.get("name").map(String::as_str).unwrap_or(""));
+                    object.id = required(&attrs, "id", index("id")?)? as u32;
+                    model.objects.push(object);
+                    let pindex = index("pindex")?.unwrap_or(0);
+                    object_property.push(index("pid")?.map(|pid| (pid as u32, pindex)));
+                    triangle_properties.push(Vec::new());
+                }
+                b"vertex" => {
+                    let object = current(&mut model.objects, "vertex")?;
+                    let coordinate = |key| required(&attrs, key, number(key)?);
+                    object.vertices.push(Vec3::new(coordinate("x")?, coordinate("y")?, coordinate("z")?));
+                }
+                b"triangle" => {
+                    let object = current(&mut model.objects, "triangle")?;
+                    let corner = |key| required(&attrs, key, index(key)?);
+                    object.add_triangle([corner("v1")?, corner("v2")?, corner("v3")?]);
+                    let property = match (index("pid")?, index("p1")?) {
+                        (Some(pid), Some(p1)) => Some((pid as u32, p1)),
+                        _ => None,
+                    };
+                    if let Some(properties) = triangle_properties.last_mut() {
+                        properties.push(property);
+                    }
+                }
+                b"component" => {
+                    log::warn!("3MF: components are not supported and were skipped");
+                }
+                b"item" => {
+                    let object_id = required(&attrs, "objectid", index("objectid")?)? as u32;
+                    let mut item = ThreeMfItem::new(object_id);
+                    if let Some(transform) = attrs.get("transform") {
+                        item.transform = parse_transform(transform)?;
+                    }
+                    model.items.push(item);
+                }
+                _ => {}
+            }
+        }
+
+        let resolve = |property: Option<(u32, usize)>| -> ThreeMfResult<Option<usize>> {
+            let Some((pid, index)) = property else {
+                return Ok(None);
+            };
+            match groups.get(&pid) {
+                Some(group) => group.get(index).copied().map(Some).ok_or_else(|| {
+                    ThreeMfError::InvalidFile(format!("Material group {} has no entry {}", pid, index))
+                }),
+                // Property groups from extensions (colors, textures) are not read
+                None => Ok(None),
+            }
+        };
+        for (object, (property, triangles)) in model
+            .objects
+            .iter_mut()
+            .zip(object_property.into_iter().zip(triangle_properties))
+        {
+            object.material = resolve(property)?;
+            for (triangle, property) in object.triangles.iter_mut().zip(triangles) {
+                triangle.material = resolve(property)?.filter(|&m| Some(m) != object.material);
+            }
+        }
+
+        if self.validate_model {
+            model.validate()?;
+        }
+        Ok(model)
+    }
+}
+
+impl Default for ThreeMfReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 3MF file writer
+pub struct ThreeMfWriter {
+    precision: usize,
+}
+
+impl ThreeMfWriter {
+    /// Create a new 3MF writer
+    pub fn new() -> Self {
+        Self { precision: 6 }
+    }
+
+    /// Set decimal places for coordinates
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Write 3MF package to file
+    pub fn write_file<P: AsRef<Path>>(&self, model: &ThreeMfModel, path: P) -> ThreeMfResult<()> {
+        let writer = self.write(model, BufWriter::new(File::create(path)?))?;
+        writer.into_inner().map_err(|e| e.into_error())?;
+        Ok(())
+    }
+
+    /// Write 3MF package to a seekable writer, returning the writer
+    pub fn write<W: Write + Seek>(&self, model: &ThreeMfModel, writer: W) -> ThreeMfResult<W> {
+        model.validate()?;
+        let mut zip = ZipWriter::new(writer);
+        let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        zip.start_file(CONTENT_TYPES_PATH, deflated)?;
+        zip.write_all(CONTENT_TYPES.as_bytes())?;
+        zip.start_file(RELATIONSHIPS_PATH, deflated)?;
+        zip.write_all(relationships().as_bytes())?;
+        zip.start_file(MODEL_PATH, deflated)?;
+        zip.write_all(self.model_xml(model).as_bytes())?;
+        Ok(zip.finish()?)
+    }
+
+    /// XML of the model part
+    ///
+    /// All materials go into one base material group, whose id follows the
+    /// objects' ids.
+    pub fn model_xml(&self, model: &ThreeMfModel) -> String {
+        let p = self.precision;
+        let group_id = model.objects.iter().map(|o| o.id).max().unwrap_or(0) + 1;
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<model unit=\"{}\" xml:lang=\"en-US\" xmlns=\"{}\">",
+            model.unit.as_str(),
+            CORE_NAMESPACE
+        );
+        let version = env!("CARGO_PKG_VERSION");
+        let _ = writeln!(xml, " <metadata name=\"Application\">CADDY {}</metadata>", version);
+        xml.push_str(" <resources>\n");
+
+        if !model.materials.is_empty() {
+            let _ = writeln!(xml, "  <basematerials id=\"{}\">", group_id);
+            for material in &model.materials {
+                let c = material.color;
+                let _ = writeln!(
+                    xml,
+                    "   <base name=\"{}\" displaycolor=\"#{:02X}{:02X}{:02X}FF\"/>",
+                    escape(&material.name),
+                    c.r,
+                    c.g,
+                    c.b
+                );
+            }
+            xml.push_str("  </basematerials>\n");
+        }
+
+        for object in &model.objects {
+            let _ = write!(xml, "  <object id=\"{}\" type=\"model\"", object.id);
+            if !object.name.is_empty() {
+                let _ = write!(xml, " name=\"{}\"", escape(&object.name));
+            }
+            if let Some(material) = object.material {
+                let _ = write!(xml, " pid=\"{}\" pindex=\"{}\"", group_id, material);
+            }
+            xml.push_str(">\n   <mesh>\n    <vertices>\n");
+            for v in &object.vertices {
+                let _ = writeln!(
+                    xml,
+                    "     <vertex x=\"{:.p$}\" y=\"{:.p$}\" z=\"{:.p$}\"/>",
+                    v.x,
+                    v.y,
+                    v.z,
+                    p = p
+                );
+            }
+            xml.push_str("    </vertices>\n    <triangles>\n");
+            for t in &object.triangles {
+                let [v1, v2, v3] = t.vertices;
+                let _ = write!(xml, "     <triangle v1=\"{}\" v2=\"{}\" v3=\"{}\"", v1, v2, v3);
+                if let Some(material) = t.material {
+                    let _ = write!(xml, " pid=\"{}\" p1=\"{}\"", group_id, material);
+                }
+                xml.push_str("/>\n");
+            }
+            xml.push_str("    </triangles>\n   </mesh>\n  </object>\n");
+        }
+        xml.push_str(" </resources>\n <build>\n");
+
+        for item in &model.items {
+            let _ = write!(xml, "  <item objectid=\"{}\"", item.object_id);
+            if item.transform != ThreeMfItem::IDENTITY {
+                let values: Vec<String> =
+                    item.transform.iter().map(|m| format!("{:.p$}", m, p = p)).collect();
+                let _ = write!(xml, " transform=\"{}\"", values.join(" "));
+            }
+            xml.push_str("/>\n");
+        }
+        xml.push_str(" </build>\n</model>\n");
+        xml
+    }
+}
+
+impl Default for ThreeMfWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const CONTENT_TYPES: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">
+ <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>
+ <Default Extension=\"model\" ContentType=\"application/vnd.ms-package.3dmanufacturing-3dmodel+xml\"/>
+</Types>
+";
+
+fn relationships() -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\n \
+         <Relationship Target=\"/{}\" Id=\"rel0\" Type=\"{}\"/>\n\
+         </Relationships>\n",
+        MODEL_PATH, MODEL_RELATIONSHIP
+    )
+}
+
+/// Package path of the model part named by the package relationships
+fn model_target(rels: &str) -> ThreeMfResult<Option<String>> {
+    let mut reader = Reader::from_str(rels);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                let attrs = attributes(&e)?;
+                if attrs.get("Type").map(String::as_str) == Some(MODEL_RELATIONSHIP) {
+                    return Ok(attrs.get("Target").map(|t| t.trim_start_matches('/').to_string()));
+                }
+            }
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+/// Attributes of an element by local name, unescaped
+fn attributes(e: &BytesStart) -> ThreeMfResult<HashMap<String, String>> {
+    let mut attrs = HashMap::new();
+    for attr in e.attributes() {
+        let attr = attr.map_err(|e| ThreeMfError::Xml(e.to_string()))?;
+        let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).into_owned();
+        attrs.insert(key, attr.unescape_value()?.into_owned());
+    }
+    Ok(attrs)
+}
+
+fn required<T>(attrs: &HashMap<String, String>, key: &str, value: Option<T>) -> ThreeMfResult<T> {
+    value.ok_or_else(|| {
+        let element = attrs.get("name").map(|n| format!(" of {}", n)).unwrap_or_default();
+        ThreeMfError::InvalidFile(format!("Missing {} attribute{}", key, element))
+    })
+}
+
+fn current<'a>(objects: &'a mut [ThreeMfObject], element: &str) -> ThreeMfResult<&'a mut ThreeMfObject> {
+    objects
+        .last_mut()
+        .ok_or_else(|| ThreeMfError::InvalidFile(format!("{} outside an object", element)))
+}
+
+fn parse_number(key: &str, value: &str) -> ThreeMfResult<f64> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .ok_or_else(|| ThreeMfError::InvalidFile(format!("Invalid {}: {}", key, value)))
+}
+
+fn parse_transform(value: &str) -> ThreeMfResult<[f64; 12]> {
+    let numbers = value
+        .split_whitespace()
+        .map(|n| parse_number("transform", n))
+        .collect::<ThreeMfResult<Vec<f64>>>()?;
+    numbers
+        .try_into()
+        .map_err(|_| ThreeMfError::InvalidFile(format!("Transform needs 12 numbers: {}", value)))
+}
+
+/// Color of a `#RRGGBB` or `#RRGGBBAA` display color; alpha is dropped
+fn parse_color(value: &str) -> ThreeMfResult<Color> {
+    let invalid = || ThreeMfError::InvalidFile(format!("Invalid color: {}", value));
+    let hex = value.strip_prefix('#').filter(|h| h.len() == 6 || h.len() == 8).ok_or_else(invalid)?;
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .ok_or_else(invalid)
+    };
+    Ok(Color::new(channel(0)?, channel(2)?, channel(4)?))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn cube(model: &mut ThreeMfModel) -> u32 {
+        let mut cube = ThreeMfObject::new("Cube & Co");
+        for i in 0..8 {
+            let bit = |b: usize| ((i >> b) & 1) as f64 * 10.0;
+            cube.vertices.push(Vec3::new(bit(0), bit(1), bit(2)));
+        }
+        let sides = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+        for [a, b, c, d] in sides {
+            cube.add_triangle([a, b, c]);
+            cube.add_triangle([a, c, d]);
+        }
+        model.add_object(cube)
+    }
+
+    #[test]
+    fn test_round_trip_keeps_units_colors_and_build() {
+        let mut model = ThreeMfModel::new(Unit::Inches.into());
+        let red = model.add_material("Red", Color::red());
+        let blue = model.add_material("Blue", Color::new(0, 64, 255));
+        let id = cube(&mut model);
+        model.objects[0].material = Some(red);
+        model.objects[0].triangles[0].material = Some(blue);
+        model.items.push(ThreeMfItem::new(id).translated(1.0, 2.0, 0.0));
+
+        let bytes = ThreeMfWriter::new().write(&model, Cursor::new(Vec::new())).unwrap().into_inner();
+        let read = ThreeMfReader::new().read(Cursor::new(bytes)).unwrap();
+        assert_eq!(read.unit, ThreeMfUnit::Inch);
+        assert_eq!(read.materials, model.materials);
+        assert_eq!(read.items, model.items);
+        assert_eq!(read.objects[0].name, "Cube & Co");
+        assert_eq!(read.objects[0].material, Some(red));
+        assert_eq!(read.objects[0].triangles, model.objects[0].triangles);
+        assert_eq!(read.triangle_count(), 12);
+        assert_eq!(read.objects[0].to_triangle_mesh().faces.len(), 12);
+
+        let corner = read.items[0].apply(read.objects[0].vertices[7]);
+        assert_eq!((corner.x, corner.y, corner.z), (11.0, 12.0, 10.0));
+
+        // Converting to millimeters scales geometry and placement alike
+        let mut mm = read.clone();
+        mm.convert_to(Unit::Millimeters);
+        assert_eq!(mm.unit, ThreeMfUnit::Millimeter);
+        assert!((mm.objects[0].vertices[7].x - 254.0).abs() < 1e-9);
+        assert!((mm.items[0].transform[10] - 50.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_read_rejects_bad_references() {
+        let xml = format!(
+            "<model unit=\"millimeter\" xmlns=\"{}\"><resources>\
+             <basematerials id=\"1\"><base name=\"Grey\" displaycolor=\"#808080\"/></basematerials>\
+             <object id=\"2\" pid=\"1\" pindex=\"0\"><mesh>\
+             <vertices><vertex x=\"0\" y=\"0\" z=\"0\"/><vertex x=\"1\" y=\"0\" z=\"0\"/>\
+             <vertex x=\"0\" y=\"1\" z=\"0\"/></vertices>\
+             <triangles><triangle v1=\"0\" v2=\"1\" v3=\"{}\"/></triangles>\
+             </mesh></object></resources><build><item objectid=\"{}\"/></build></model>",
+            CORE_NAMESPACE, "{v3}", "{item}"
+        );
+        let read = |v3: &str, item: &str| {
+            ThreeMfReader::new().read_model(&xml.replace("{v3}", v3).replace("{item}", item))
+        };
+
+        let model = read("2", "2").unwrap();
+        assert_eq!(model.materials[0].color, Color::new(128, 128, 128));
+        assert_eq!(model.objects[0].material, Some(0));
+        assert!(matches!(read("3", "2"), Err(ThreeMfError::InvalidFile(_))));
+        assert!(matches!(read("2", "5"), Err(ThreeMfError::InvalidFile(_))));
+        assert!(matches!(read("x", "2"), Err(ThreeMfError::InvalidFile(_))));
+    }
+}
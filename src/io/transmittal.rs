@@ -0,0 +1,1172 @@
+// CADDY - Enterprise CAD System
+// File I/O System - Electronic Transmittal Module
+
+//! Electronic transmittal (eTransmit)
+//!
+//! Packages a drawing together with everything it needs to open correctly
+//! elsewhere into a single ZIP archive:
+//!
+//! - **Xrefs**: inserts carrying an [`XREF_ATTRIBUTE`] path, or inserts of
+//!   blocks the drawing does not define (resolved as `<name>.cdy`, `.cdyj`
+//!   or `.dxf`). Xrefs are scanned in turn, so nested references travel too.
+//! - **Images**: entities carrying an [`IMAGE_ATTRIBUTE`] path
+//! - **Fonts**: text styles naming a font file (`romans.shx`, `arial.ttf`),
+//!   or a style name with a matching font file on the font search path
+//! - **Plot styles**: the [`STYLESHEET_VARIABLE`] document variable
+//!
+//! Files are laid out in one folder per kind (`xrefs/`, `images/`, `fonts/`,
+//! `plotstyles/`) and references inside packaged CADDY drawings are rewritten
+//! to those relative paths. DXF drawings are copied unchanged. The archive
+//! also holds a `manifest.json` with checksums and a human-readable
+//! `transmittal.txt`; unresolved references are listed there rather than
+//! failing the package.
+//!
+//! With a password set, the finished archive is encrypted with AES-256-GCM
+//! under an Argon2id key (see [`encrypt_archive`]).
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use caddy::io::transmittal::{Transmittal, TransmittalOptions};
+//!
+//! let options = TransmittalOptions::default()
+//!     .with_search_path("/projects/shared/xrefs")
+//!     .with_note("Issued for tender")
+//!     .with_password("s3cret");
+//! let package = Transmittal::new(options).package("site-plan.cdy").unwrap();
+//! package.write("site-plan-transmittal.zip").unwrap();
+//! println!("{}", package.report);
+//! ```
+
+use crate::enterprise::crypto::kdf::{Argon2Config, KdfProvider};
+use crate::enterprise::crypto::symmetric::{Aes256GcmCipher, EncryptedData};
+use crate::io::document::{Document, Entity, GeometryType};
+use crate::io::native::{FileFormat, FormatDetector, JsonFormat, NativeFormat};
+use chrono::{DateTime, Utc};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Entity attribute holding an external reference path
+pub const XREF_ATTRIBUTE: &str = "XREF";
+/// Entity attribute holding a raster image path
+pub const IMAGE_ATTRIBUTE: &str = "IMAGE";
+/// Document variable naming the plot style table (`.ctb`/`.stb`)
+pub const STYLESHEET_VARIABLE: &str = "STYLESHEET";
+
+/// Archive entry holding the manifest
+pub const MANIFEST_NAME: &str = "manifest.json";
+/// Archive entry holding the transmittal report
+pub const REPORT_NAME: &str = "transmittal.txt";
+
+/// Leading bytes of an encrypted package
+const ENCRYPTED_MAGIC: &[u8; 4] = b"CDYT";
+const ENCRYPTED_VERSION: u8 = 1;
+const SALT_SIZE: usize = 16;
+/// Magic, version, three Argon2 cost parameters, salt
+const HEADER_SIZE: usize = 4 + 1 + 12 + SALT_SIZE;
+
+const FONT_EXTENSIONS: &[&str] = &["shx", "ttf", "ttc", "otf"];
+const DRAWING_EXTENSIONS: &[&str] = &["cdy", "cdyj", "dxf"];
+
+/// Transmittal errors
+#[derive(Error, Debug)]
+pub enum TransmittalError {
+    /// Root drawing could not be loaded
+    #[error("Failed to load {}: {message}", path.display())]
+    Load {
+        /// Drawing path
+        path: PathBuf,
+        /// Loader error
+        message: String,
+    },
+    /// File system error
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    /// ZIP archive could not be written or read
+    #[error("Archive error: {0}")]
+    Archive(String),
+    /// Manifest could not be written or read
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    /// Key derivation or encryption failed
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+    /// Package is encrypted and no password was given
+    #[error("Package is encrypted; a password is required")]
+    PasswordRequired,
+    /// Password is wrong or the package was modified
+    #[error("Wrong password or corrupted package")]
+    Decryption,
+}
+
+impl From<zip::result::ZipError> for TransmittalError {
+    fn from(e: zip::result::ZipError) -> Self {
+        TransmittalError::Archive(e.to_string())
+    }
+}
+
+/// Result type for transmittal operations
+pub type TransmittalResult<T> = Result<T, TransmittalError>;
+
+/// Kind of packaged file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DependencyKind {
+    /// The drawing being transmitted
+    Drawing,
+    /// Externally referenced drawing
+    Xref,
+    /// Raster image
+    Image,
+    /// Font file
+    Font,
+    /// Plot style table
+    PlotStyle,
+}
+
+impl DependencyKind {
+    /// Archive folder for this kind ("" for the root drawing)
+    pub fn folder(&self) -> &'static str {
+        match self {
+            DependencyKind::Drawing => "",
+            DependencyKind::Xref => "xrefs",
+            DependencyKind::Image => "images",
+            DependencyKind::Font => "fonts",
+            DependencyKind::PlotStyle => "plotstyles",
+        }
+    }
+
+    /// Display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            DependencyKind::Drawing => "Drawing",
+            DependencyKind::Xref => "Xref",
+            DependencyKind::Image => "Image",
+            DependencyKind::Font => "Font",
+            DependencyKind::PlotStyle => "Plot style",
+        }
+    }
+}
+
+/// A file reference found in a drawing
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Reference {
+    /// What is referenced
+    pub kind: DependencyKind,
+    /// Path or name as stored in the drawing
+    pub value: String,
+}
+
+/// List the external files a document refers to, without duplicates
+pub fn references(doc: &Document) -> Vec<Reference> {
+    let mut seen = HashSet::new();
+    let mut refs = Vec::new();
+    let mut push = |kind, value: &str| {
+        let reference = Reference {
+            kind,
+            value: value.to_string(),
+        };
+        if !value.is_empty() && seen.insert(reference.clone()) {
+            refs.push(reference);
+        }
+    };
+
+    for entity in all_entities(doc) {
+        if let Some(image) = entity.attributes.get(IMAGE_ATTRIBUTE) {
+            push(DependencyKind::Image, image);
+        }
+        match &entity.geometry {
+            GeometryType::Insert(insert) => {
+                if let Some(path) = entity.attributes.get(XREF_ATTRIBUTE) {
+                    push(DependencyKind::Xref, path);
+                } else if !doc.blocks.contains_key(&insert.block_name) {
+                    push(DependencyKind::Xref, &insert.block_name);
+                }
+            }
+            GeometryType::Text(text) => push(DependencyKind::Font, &text.style),
+            GeometryType::MText(text) => push(DependencyKind::Font, &text.style),
+            _ => {}
+        }
+    }
+    if let Some(sheet) = doc.variables.get(STYLESHEET_VARIABLE) {
+        push(DependencyKind::PlotStyle, sheet);
+    }
+    refs
+}
+
+fn all_entities(doc: &Document) -> impl Iterator<Item = &Entity> {
+    doc.entities
+        .iter()
+        .chain(doc.blocks.values().flat_map(|b| b.entities.iter()))
+}
+
+/// Transmittal options
+#[derive(Debug, Clone)]
+pub struct TransmittalOptions {
+    /// Extra folders searched for xrefs, images, and plot styles
+    pub search_paths: Vec<PathBuf>,
+    /// Folders searched for font files
+    pub font_paths: Vec<PathBuf>,
+    /// Package fonts
+    pub include_fonts: bool,
+    /// Package plot style tables
+    pub include_plot_styles: bool,
+    /// Note printed on the transmittal report
+    pub note: String,
+    /// Encrypt the package with this password
+    pub password: Option<String>,
+    /// Key derivation cost for encrypted packages
+    pub kdf: Argon2Config,
+}
+
+impl Default for TransmittalOptions {
+    fn default() -> Self {
+        Self {
+            search_paths: Vec::new(),
+            font_paths: Vec::new(),
+            include_fonts: true,
+            include_plot_styles: true,
+            note: String::new(),
+            password: None,
+            kdf: Argon2Config::default(),
+        }
+    }
+}
+
+impl TransmittalOptions {
+    /// Add a search folder for xrefs, images, and plot styles
+    pub fn with_search_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.search_paths.push(path.into());
+        self
+    }
+
+    /// Add a font folder
+    pub fn with_font_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.font_paths.push(path.into());
+        self
+    }
+
+    /// Set the report note
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = note.into();
+        self
+    }
+
+    /// Encrypt the package
+    pub fn with_password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+}
+
+/// One packaged file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Path inside the archive
+    pub archive_path: String,
+    /// What the file is
+    pub kind: DependencyKind,
+    /// Where the file was collected from
+    pub source: PathBuf,
+    /// Size in bytes as packaged
+    pub size: u64,
+    /// SHA-256 of the packaged bytes, hex encoded
+    pub sha256: String,
+    /// Whether references inside the file were rewritten to archive paths
+    pub remapped: bool,
+}
+
+/// A reference that could not be resolved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissingDependency {
+    /// What was referenced
+    pub kind: DependencyKind,
+    /// Path or name as stored in the drawing
+    pub reference: String,
+    /// Archive path of the drawing holding the reference
+    pub referenced_by: String,
+}
+
+/// Package contents, stored as `manifest.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransmittalManifest {
+    /// Package name (root drawing file stem)
+    pub name: String,
+    /// When the package was built
+    pub created: DateTime<Utc>,
+    /// Archive path of the root drawing
+    pub root: String,
+    /// Report note
+    pub note: String,
+    /// Whether the package is password protected
+    pub encrypted: bool,
+    /// Packaged files, root drawing first
+    pub files: Vec<ManifestEntry>,
+    /// References left out of the package
+    pub missing: Vec<MissingDependency>,
+}
+
+impl TransmittalManifest {
+    /// Packaged files of one kind
+    pub fn files_of(&self, kind: DependencyKind) -> impl Iterator<Item = &ManifestEntry> {
+        self.files.iter().filter(move |f| f.kind == kind)
+    }
+
+    /// Whether every reference was packaged
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    /// Render the transmittal report
+    pub fn report(&self) -> String {
+        let mut s = String::new();
+        let _ = writeln!(s, "TRANSMITTAL: {}", self.name);
+        let _ = writeln!(
+            s,
+            "Created:   {}",
+            self.created.format("%Y-%m-%d %H:%M UTC")
+        );
+        let _ = writeln!(s, "Drawing:   {}", self.root);
+        let _ = writeln!(
+            s,
+            "Protected: {}",
+            if self.encrypted {
+                "yes (password)"
+            } else {
+                "no"
+            }
+        );
+        if !self.note.is_empty() {
+            let _ = writeln!(s, "Note:      {}", self.note);
+        }
+
+        let width = self
+            .files
+            .iter()
+            .map(|f| f.archive_path.len())
+            .max()
+            .unwrap_or(0);
+        let total: u64 = self.files.iter().map(|f| f.size).sum();
+        let _ = writeln!(s, "\nFiles ({}, {} bytes)", self.files.len(), total);
+        for file in &self.files {
+            let _ = writeln!(
+                s,
+                "  {:<10}  {:<width$}  {:>10}  {}",
+                file.kind.name(),
+                file.archive_path,
+                file.size,
+                file.source.display(),
+            );
+        }
+
+        if !self.missing.is_empty() {
+            let _ = writeln!(s, "\nMissing ({})", self.missing.len());
+            for missing in &self.missing {
+                let _ = writeln!(
+                    s,
+                    "  {:<10}  {}  (referenced by {})",
+                    missing.kind.name(),
+                    missing.reference,
+                    missing.referenced_by
+                );
+            }
+        }
+        s
+    }
+}
+
+/// A finished package
+#[derive(Debug, Clone)]
+pub struct TransmittalPackage {
+    /// What was packaged
+    pub manifest: TransmittalManifest,
+    /// Transmittal report text
+    pub report: String,
+    /// ZIP archive, or the encrypted container when a password was set
+    pub archive: Vec<u8>,
+}
+
+impl TransmittalPackage {
+    /// Write the archive to `path` and the report next to it as `.txt`
+    pub fn write(&self, path: impl AsRef<Path>) -> TransmittalResult<()> {
+        let path = path.as_ref();
+        fs::write(path, &self.archive)?;
+        fs::write(path.with_extension("txt"), &self.report)?;
+        Ok(())
+    }
+}
+
+/// Builds transmittal packages
+#[derive(Debug, Clone, Default)]
+pub struct Transmittal {
+    options: TransmittalOptions,
+}
+
+impl Transmittal {
+    /// Create a packager
+    pub fn new(options: TransmittalOptions) -> Self {
+        Self { options }
+    }
+
+    /// Package a drawing and its dependencies
+    pub fn package(&self, root: impl AsRef<Path>) -> TransmittalResult<TransmittalPackage> {
+        let root = root.as_ref();
+        let mut collector = Collector::new(&self.options);
+        let root_doc = load(root)?;
+        collector.collect(root, root_doc)?;
+
+        let name = root
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "transmittal".to_string());
+        let mut manifest = TransmittalManifest {
+            name,
+            created: Utc::now(),
+            root: collector.files[0].archive_path.clone(),
+            note: self.options.note.clone(),
+            encrypted: self.options.password.is_some(),
+            files: Vec::new(),
+            missing: collector.missing.clone(),
+        };
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        for file in &collector.files {
+            let (bytes, remapped) = collector.packaged_bytes(file)?;
+            zip.start_file(file.archive_path.as_str(), deflated)?;
+            zip.write_all(&bytes)?;
+            manifest.files.push(ManifestEntry {
+                archive_path: file.archive_path.clone(),
+                kind: file.kind,
+                source: file.source.clone(),
+                size: bytes.len() as u64,
+                sha256: hex::encode(Sha256::digest(&bytes)),
+                remapped,
+            });
+        }
+
+        let report = manifest.report();
+        zip.start_file(MANIFEST_NAME, deflated)?;
+        zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+        zip.start_file(REPORT_NAME, deflated)?;
+        zip.write_all(report.as_bytes())?;
+        let mut archive = zip.finish()?.into_inner();
+
+        if let Some(password) = &self.options.password {
+            archive = encrypt_archive(&archive, password, &self.options.kdf)?;
+        }
+        for missing in &manifest.missing {
+            log::warn!(
+                "Transmittal {}: {} '{}' not found (referenced by {})",
+                manifest.name,
+                missing.kind.name(),
+                missing.reference,
+                missing.referenced_by
+            );
+        }
+        Ok(TransmittalPackage {
+            manifest,
+            report,
+            archive,
+        })
+    }
+}
+
+/// A file queued for the archive
+struct PackagedFile {
+    kind: DependencyKind,
+    source: PathBuf,
+    archive_path: String,
+    /// Loaded drawing and where each of its references was packaged
+    drawing: Option<(Document, HashMap<Reference, String>)>,
+}
+
+struct Collector<'a> {
+    options: &'a TransmittalOptions,
+    files: Vec<PackagedFile>,
+    by_source: HashMap<PathBuf, usize>,
+    archive_names: HashSet<String>,
+    missing: Vec<MissingDependency>,
+}
+
+impl<'a> Collector<'a> {
+    fn new(options: &'a TransmittalOptions) -> Self {
+        Self {
+            options,
+            files: Vec::new(),
+            by_source: HashMap::new(),
+            archive_names: HashSet::new(),
+            missing: Vec::new(),
+        }
+    }
+
+    /// Walk the root drawing and every xref reachable from it
+    fn collect(&mut self, root: &Path, root_doc: Document) -> TransmittalResult<()> {
+        let root_index = self.register(root, DependencyKind::Drawing)?;
+        self.files[root_index].drawing = Some((root_doc, HashMap::new()));
+        let mut queue = VecDeque::from([root_index]);
+
+        while let Some(index) = queue.pop_front() {
+            let Some((doc, _)) = &self.files[index].drawing else {
+                continue;
+            };
+            let refs = references(doc);
+            let dir = self.files[index]
+                .source
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            let mut placed = HashMap::new();
+
+            for reference in refs {
+                if !self.wanted(reference.kind) {
+                    continue;
+                }
+                let Some(path) = self.resolve(&reference, &dir) else {
+                    // A bare style name is usually a built-in style, not a file
+                    if reference.kind != DependencyKind::Font
+                        || has_font_extension(&reference.value)
+                    {
+                        self.missing.push(MissingDependency {
+                            kind: reference.kind,
+                            reference: reference.value.clone(),
+                            referenced_by: self.files[index].archive_path.clone(),
+                        });
+                    }
+                    continue;
+                };
+                let known = self.by_source.len();
+                let target = self.register(&path, reference.kind)?;
+                if reference.kind == DependencyKind::Xref && self.by_source.len() > known {
+                    match load(&path) {
+                        Ok(doc) => {
+                            self.files[target].drawing = Some((doc, HashMap::new()));
+                            queue.push_back(target);
+                        }
+                        Err(e) => {
+                            log::warn!("Xref {} copied without scanning: {}", path.display(), e)
+                        }
+                    }
+                }
+                placed.insert(reference, self.files[target].archive_path.clone());
+            }
+            if let Some((_, map)) = &mut self.files[index].drawing {
+                *map = placed;
+            }
+        }
+        Ok(())
+    }
+
+    fn wanted(&self, kind: DependencyKind) -> bool {
+        match kind {
+            DependencyKind::Font => self.options.include_fonts,
+            DependencyKind::PlotStyle => self.options.include_plot_styles,
+            _ => true,
+        }
+    }
+
+    /// Add a file to the package once, returning its index
+    fn register(&mut self, path: &Path, kind: DependencyKind) -> TransmittalResult<usize> {
+        let source = path.canonicalize()?;
+        if let Some(&index) = self.by_source.get(&source) {
+            return Ok(index);
+        }
+
+        let file_name = source
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let folder = kind.folder();
+        let join = |name: &str| {
+            if folder.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}/{}", folder, name)
+            }
+        };
+        // Same file name from different folders: number the later ones
+        let mut archive_path = join(&file_name);
+        let mut n = 1;
+        while archive_path == MANIFEST_NAME
+            || archive_path == REPORT_NAME
+            || self.archive_names.contains(&archive_path.to_lowercase())
+        {
+            let (stem, ext) = split_extension(&file_name);
+            archive_path = join(&format!("{}-{}{}", stem, n, ext));
+            n += 1;
+        }
+        self.archive_names.insert(archive_path.to_lowercase());
+
+        self.files.push(PackagedFile {
+            kind,
+            source: source.clone(),
+            archive_path,
+            drawing: None,
+        });
+        self.by_source.insert(source, self.files.len() - 1);
+        Ok(self.files.len() - 1)
+    }
+
+    /// Find the file a reference points at
+    fn resolve(&self, reference: &Reference, drawing_dir: &Path) -> Option<PathBuf> {
+        let value = reference.value.as_str();
+        let mut names = vec![value.to_string()];
+        match reference.kind {
+            DependencyKind::Xref if extension(value).is_none() => {
+                names.extend(
+                    DRAWING_EXTENSIONS
+                        .iter()
+                        .map(|e| format!("{}.{}", value, e)),
+                );
+            }
+            DependencyKind::Font if !has_font_extension(value) => {
+                names = FONT_EXTENSIONS
+                    .iter()
+                    .map(|e| format!("{}.{}", value, e))
+                    .collect();
+            }
+            _ => {}
+        }
+
+        let mut dirs = vec![drawing_dir.to_path_buf()];
+        if reference.kind == DependencyKind::Font {
+            dirs.extend(self.options.font_paths.iter().cloned());
+        }
+        dirs.extend(self.options.search_paths.iter().cloned());
+
+        names.iter().find_map(|name| locate(name, &dirs))
+    }
+
+    /// Bytes to store for a file, and whether its references were rewritten
+    fn packaged_bytes(&self, file: &PackagedFile) -> TransmittalResult<(Vec<u8>, bool)> {
+        let format = FormatDetector::detect(&file.source).ok();
+        let rewritable = matches!(
+            format,
+            Some(FileFormat::NativeBinary) | Some(FileFormat::NativeJson)
+        );
+        let Some((doc, placed)) = file
+            .drawing
+            .as_ref()
+            .filter(|(_, p)| rewritable && !p.is_empty())
+        else {
+            return Ok((fs::read(&file.source)?, false));
+        };
+
+        let mut doc = doc.clone();
+        let relative: HashMap<&Reference, String> = placed
+            .iter()
+            .map(|(r, target)| (r, relative_path(&file.archive_path, target)))
+            .collect();
+        remap(&mut doc, &relative);
+
+        let bytes = if format == Some(FileFormat::NativeJson) {
+            JsonFormat::new()
+                .to_string(&doc)
+                .map_err(|e| TransmittalError::Archive(e.to_string()))?
+                .into_bytes()
+        } else {
+            let temp =
+                std::env::temp_dir().join(format!("caddy-transmittal-{}.cdy", Uuid::new_v4()));
+            let bytes = match NativeFormat::new().save(&doc, &temp) {
+                Ok(()) => fs::read(&temp).map_err(TransmittalError::from),
+                Err(e) => Err(TransmittalError::Archive(e.to_string())),
+            };
+            let _ = fs::remove_file(&temp);
+            bytes?
+        };
+        Ok((bytes, true))
+    }
+}
+
+/// Point a drawing's references at their packaged locations
+fn remap(doc: &mut Document, relative: &HashMap<&Reference, String>) {
+    let lookup = |kind, value: &str| {
+        relative
+            .get(&Reference {
+                kind,
+                value: value.to_string(),
+            })
+            .cloned()
+    };
+    let local_blocks: HashSet<String> = doc.blocks.keys().cloned().collect();
+    let entities = doc
+        .entities
+        .iter_mut()
+        .chain(doc.blocks.values_mut().flat_map(|b| b.entities.iter_mut()));
+
+    for entity in entities {
+        if let Some(image) = entity.attributes.get(IMAGE_ATTRIBUTE) {
+            if let Some(path) = lookup(DependencyKind::Image, image) {
+                entity.attributes.insert(IMAGE_ATTRIBUTE.to_string(), path);
+            }
+        }
+        match &mut entity.geometry {
+            GeometryType::Insert(insert) => {
+                // Implicit xrefs gain an explicit path so the packaged copy
+                // no longer depends on the search path
+                let stored = match entity.attributes.get(XREF_ATTRIBUTE) {
+                    Some(path) => Some(path.clone()),
+                    None if !local_blocks.contains(&insert.block_name) => {
+                        Some(insert.block_name.clone())
+                    }
+                    None => None,
+                };
+                if let Some(path) = stored.and_then(|s| lookup(DependencyKind::Xref, &s)) {
+                    entity.attributes.insert(XREF_ATTRIBUTE.to_string(), path);
+                }
+            }
+            GeometryType::Text(text) if has_font_extension(&text.style) => {
+                if let Some(path) = lookup(DependencyKind::Font, &text.style) {
+                    text.style = path;
+                }
+            }
+            GeometryType::MText(text) if has_font_extension(&text.style) => {
+                if let Some(path) = lookup(DependencyKind::Font, &text.style) {
+                    text.style = path;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(sheet) = doc.variables.get(STYLESHEET_VARIABLE) {
+        if let Some(path) = lookup(DependencyKind::PlotStyle, sheet) {
+            doc.variables.insert(STYLESHEET_VARIABLE.to_string(), path);
+        }
+    }
+}
+
+/// Find the file a stored reference path points at
+///
+/// Absolute paths are used when they exist; otherwise the path is tried
+/// relative to each folder in `dirs`, in order, then by bare file name,
+/// which also catches absolute paths from another machine.
+pub fn locate(name: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    let path = Path::new(name);
+    if path.is_absolute() && path.is_file() {
+        return Some(path.to_path_buf());
+    }
+    dirs.iter()
+        .flat_map(|dir| [dir.join(path), dir.join(file_name(name))])
+        .find(|candidate| candidate.is_file())
+}
+
+fn load(path: &Path) -> TransmittalResult<Document> {
+    FormatDetector::load(path).map_err(|e| TransmittalError::Load {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    })
+}
+
+/// Path of `to` as seen from the folder holding `from` (both archive paths)
+fn relative_path(from: &str, to: &str) -> String {
+    let from_dir = from.rsplit_once('/').map(|(d, _)| d).unwrap_or("");
+    match to.rsplit_once('/') {
+        _ if from_dir.is_empty() => to.to_string(),
+        Some((dir, name)) if dir == from_dir => name.to_string(),
+        _ => format!("../{}", to),
+    }
+}
+
+/// Last component of a path written with either separator
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+fn extension(path: &str) -> Option<&str> {
+    file_name(path).rsplit_once('.').map(|(_, ext)| ext)
+}
+
+fn has_font_extension(path: &str) -> bool {
+    extension(path).is_some_and(|ext| FONT_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+fn split_extension(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(i) if i > 0 => (&name[..i], &name[i..]),
+        _ => (name, ""),
+    }
+}
+
+/// Whether package bytes are an encrypted container
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(ENCRYPTED_MAGIC)
+}
+
+/// Encrypt a package archive with a password
+///
+/// Layout: `CDYT`, version byte, Argon2id memory/time/parallelism costs
+/// (u32 LE), 16-byte salt, then the AES-256-GCM nonce and ciphertext. The
+/// header is authenticated as associated data, so the cost parameters
+/// cannot be altered without failing decryption.
+pub fn encrypt_archive(
+    archive: &[u8],
+    password: &str,
+    kdf: &Argon2Config,
+) -> TransmittalResult<Vec<u8>> {
+    let mut salt = [0u8; SALT_SIZE];
+    OsRng.fill_bytes(&mut salt);
+
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    header.extend_from_slice(ENCRYPTED_MAGIC);
+    header.push(ENCRYPTED_VERSION);
+    header.extend_from_slice(&kdf.memory_cost.to_le_bytes());
+    header.extend_from_slice(&kdf.time_cost.to_le_bytes());
+    header.extend_from_slice(&kdf.parallelism.to_le_bytes());
+    header.extend_from_slice(&salt);
+
+    let cipher = cipher(password, &salt, kdf)?;
+    let encrypted = cipher
+        .encrypt(archive, Some(&header))
+        .map_err(|e| TransmittalError::Encryption(e.to_string()))?;
+
+    let mut out = header;
+    out.extend_from_slice(&encrypted.to_bytes());
+    Ok(out)
+}
+
+/// Recover the ZIP archive from an encrypted package
+pub fn decrypt_archive(data: &[u8], password: &str) -> TransmittalResult<Vec<u8>> {
+    if !is_encrypted(data) || data.len() < HEADER_SIZE {
+        return Err(TransmittalError::Archive(
+            "not an encrypted package".to_string(),
+        ));
+    }
+    if data[4] != ENCRYPTED_VERSION {
+        return Err(TransmittalError::Archive(format!(
+            "unsupported package version {}",
+            data[4]
+        )));
+    }
+
+    let cost = |at: usize| u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+    let kdf = Argon2Config {
+        memory_cost: cost(5),
+        time_cost: cost(9),
+        parallelism: cost(13),
+        ..Argon2Config::default()
+    };
+    let (header, body) = data.split_at(HEADER_SIZE);
+    let salt = &header[HEADER_SIZE - SALT_SIZE..];
+
+    let mut encrypted = EncryptedData::from_bytes(body, Aes256GcmCipher::NONCE_SIZE)
+        .map_err(|_| TransmittalError::Decryption)?;
+    encrypted.associated_data = header.to_vec();
+    cipher(password, salt, &kdf)?
+        .decrypt(&encrypted)
+        .map_err(|_| TransmittalError::Decryption)
+}
+
+fn cipher(password: &str, salt: &[u8], kdf: &Argon2Config) -> TransmittalResult<Aes256GcmCipher> {
+    let key = KdfProvider::derive_argon2id(password.as_bytes(), salt, kdf)
+        .map_err(|e| TransmittalError::Encryption(e.to_string()))?;
+    Aes256GcmCipher::new(key.as_bytes()).map_err(|e| TransmittalError::Encryption(e.to_string()))
+}
+
+/// Read the manifest of a package, decrypting it first if needed
+pub fn read_manifest(
+    data: &[u8],
+    password: Option<&str>,
+) -> TransmittalResult<TransmittalManifest> {
+    let bytes = read_entry(data, password, MANIFEST_NAME)?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Read one file out of a package, decrypting it first if needed
+pub fn read_entry(data: &[u8], password: Option<&str>, name: &str) -> TransmittalResult<Vec<u8>> {
+    let decrypted;
+    let archive = if is_encrypted(data) {
+        let password = password.ok_or(TransmittalError::PasswordRequired)?;
+        decrypted = decrypt_archive(data, password)?;
+        decrypted.as_slice()
+    } else {
+        data
+    };
+
+    let mut zip = ZipArchive::new(Cursor::new(archive))?;
+    let mut entry = zip.by_name(name)?;
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{Block, Insert, Text, TextAlignment, Vec3};
+
+    fn insert(block: &str, xref: Option<&str>) -> Entity {
+        let mut entity = Entity::new(
+            GeometryType::Insert(Insert {
+                block_name: block.to_string(),
+                position: Vec3::new(0.0, 0.0, 0.0),
+                scale: Vec3::new(1.0, 1.0, 1.0),
+                rotation: 0.0,
+                attributes: HashMap::new(),
+            }),
+            "0".to_string(),
+        );
+        if let Some(path) = xref {
+            entity
+                .attributes
+                .insert(XREF_ATTRIBUTE.to_string(), path.to_string());
+        }
+        entity
+    }
+
+    fn text(style: &str) -> Entity {
+        Entity::new(
+            GeometryType::Text(Text {
+                position: Vec3::new(0.0, 0.0, 0.0),
+                text: "NOTE".to_string(),
+                height: 2.5,
+                rotation: 0.0,
+                style: style.to_string(),
+                horizontal_alignment: TextAlignment::Left,
+                vertical_alignment: TextAlignment::Bottom,
+            }),
+            "0".to_string(),
+        )
+    }
+
+    fn save(doc: &Document, path: &Path) {
+        fs::write(path, JsonFormat::new().to_string(doc).unwrap()).unwrap();
+    }
+
+    /// Project tree: site.cdyj -> base/grid.cdyj (+ its image), a logo, a
+    /// font, a plot style from the shared folder, and a missing image
+    fn project() -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("transmittal-{}", Uuid::new_v4()));
+        let shared = dir.join("shared");
+        fs::create_dir_all(dir.join("base")).unwrap();
+        fs::create_dir_all(&shared).unwrap();
+        fs::write(dir.join("logo.png"), b"png").unwrap();
+        fs::write(dir.join("base/logo.png"), b"other png").unwrap();
+        fs::write(dir.join("romans.shx"), b"shx").unwrap();
+        fs::write(shared.join("mono.ctb"), b"ctb").unwrap();
+
+        let mut grid = Document::new();
+        let mut photo = text("Standard");
+        photo
+            .attributes
+            .insert(IMAGE_ATTRIBUTE.to_string(), "logo.png".to_string());
+        grid.add_entity(photo);
+        save(&grid, &dir.join("base/grid.cdyj"));
+
+        let mut site = Document::new();
+        site.add_entity(insert("GRID", Some("base/grid.cdyj")));
+        site.add_entity(insert("DOOR", None));
+        site.add_block(Block {
+            name: "DOOR".to_string(),
+            base_point: Vec3::new(0.0, 0.0, 0.0),
+            entities: vec![text("romans.shx")],
+            description: String::new(),
+        });
+        let mut logo = text("Standard");
+        logo.attributes.insert(
+            IMAGE_ATTRIBUTE.to_string(),
+            "C:\\Proj\\logo.png".to_string(),
+        );
+        site.add_entity(logo);
+        let mut survey = text("Standard");
+        survey
+            .attributes
+            .insert(IMAGE_ATTRIBUTE.to_string(), "survey.tif".to_string());
+        site.add_entity(survey);
+        site.variables
+            .insert(STYLESHEET_VARIABLE.to_string(), "mono.ctb".to_string());
+        let root = dir.join("site.cdyj");
+        save(&site, &root);
+        (dir, root)
+    }
+
+    #[test]
+    fn test_references() {
+        let mut doc = Document::new();
+        doc.add_entity(insert("TREE", None));
+        doc.add_entity(insert("TREE", None));
+        doc.add_entity(insert("X", Some("../base.dxf")));
+        doc.add_entity(text("arial.ttf"));
+        doc.variables
+            .insert(STYLESHEET_VARIABLE.to_string(), "acad.ctb".to_string());
+
+        let refs = references(&doc);
+        let kinds: Vec<_> = refs.iter().map(|r| (r.kind, r.value.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (DependencyKind::Xref, "TREE"),
+                (DependencyKind::Xref, "../base.dxf"),
+                (DependencyKind::Font, "arial.ttf"),
+                (DependencyKind::PlotStyle, "acad.ctb"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relative_paths() {
+        assert_eq!(relative_path("site.cdy", "images/a.png"), "images/a.png");
+        assert_eq!(relative_path("xrefs/grid.cdy", "xrefs/b.cdy"), "b.cdy");
+        assert_eq!(
+            relative_path("xrefs/grid.cdy", "images/a.png"),
+            "../images/a.png"
+        );
+        assert_eq!(file_name("C:\\Proj\\logo.png"), "logo.png");
+        assert!(has_font_extension("Fonts/ROMANS.SHX"));
+        assert!(!has_font_extension("Standard"));
+    }
+
+    #[test]
+    fn test_package_collects_and_remaps() {
+        let (dir, root) = project();
+        let options = TransmittalOptions::default()
+            .with_search_path(dir.join("shared"))
+            .with_note("For coordination");
+        let package = Transmittal::new(options).package(&root).unwrap();
+        let manifest = &package.manifest;
+
+        let paths: Vec<&str> = manifest
+            .files
+            .iter()
+            .map(|f| f.archive_path.as_str())
+            .collect();
+        assert_eq!(manifest.root, "site.cdyj");
+        assert_eq!(paths[0], "site.cdyj");
+        assert!(paths.contains(&"xrefs/grid.cdyj"));
+        assert!(paths.contains(&"images/logo.png"));
+        assert!(paths.contains(&"images/logo-1.png"));
+        assert!(paths.contains(&"fonts/romans.shx"));
+        assert!(paths.contains(&"plotstyles/mono.ctb"));
+        assert_eq!(manifest.files_of(DependencyKind::Image).count(), 2);
+
+        // DOOR is a local block and the survey image does not exist
+        assert_eq!(manifest.missing.len(), 1);
+        assert_eq!(manifest.missing[0].reference, "survey.tif");
+        assert_eq!(manifest.missing[0].referenced_by, "site.cdyj");
+        assert!(package.report.contains("For coordination"));
+        assert!(package.report.contains("survey.tif"));
+
+        let site_bytes = read_entry(&package.archive, None, "site.cdyj").unwrap();
+        let site = JsonFormat::new()
+            .from_string(std::str::from_utf8(&site_bytes).unwrap())
+            .unwrap();
+        assert_eq!(site.variables[STYLESHEET_VARIABLE], "plotstyles/mono.ctb");
+        let attrs: Vec<Option<&String>> = site
+            .entities
+            .iter()
+            .map(|e| {
+                e.attributes
+                    .get(XREF_ATTRIBUTE)
+                    .or(e.attributes.get(IMAGE_ATTRIBUTE))
+            })
+            .collect();
+        assert!(attrs.contains(&Some(&"xrefs/grid.cdyj".to_string())));
+        assert!(attrs.contains(&Some(&"images/logo.png".to_string())));
+        assert!(site.blocks["DOOR"]
+            .entities
+            .iter()
+            .any(|e| match &e.geometry {
+                GeometryType::Text(t) => t.style == "fonts/romans.shx",
+                _ => false,
+            }));
+
+        let grid_bytes = read_entry(&package.archive, None, "xrefs/grid.cdyj").unwrap();
+        let grid = JsonFormat::new()
+            .from_string(std::str::from_utf8(&grid_bytes).unwrap())
+            .unwrap();
+        assert_eq!(
+            grid.entities[0].attributes[IMAGE_ATTRIBUTE],
+            "../images/logo-1.png"
+        );
+
+        let logo = read_entry(&package.archive, None, "images/logo.png").unwrap();
+        let entry = manifest
+            .files
+            .iter()
+            .find(|f| f.archive_path == "images/logo.png")
+            .unwrap();
+        assert_eq!(entry.sha256, hex::encode(Sha256::digest(&logo)));
+        assert!(!entry.remapped);
+
+        assert_eq!(read_manifest(&package.archive, None).unwrap(), *manifest);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_xref_cycles_and_options() {
+        let dir = std::env::temp_dir().join(format!("transmittal-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut a = Document::new();
+        a.add_entity(insert("B", None));
+        a.add_entity(text("romans.shx"));
+        save(&a, &dir.join("A.cdyj"));
+        let mut b = Document::new();
+        b.add_entity(insert("A", None));
+        save(&b, &dir.join("B.cdyj"));
+
+        let options = TransmittalOptions {
+            include_fonts: false,
+            ..Default::default()
+        };
+        let package = Transmittal::new(options)
+            .package(dir.join("A.cdyj"))
+            .unwrap();
+        let paths: Vec<&str> = package
+            .manifest
+            .files
+            .iter()
+            .map(|f| f.archive_path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["A.cdyj", "xrefs/B.cdyj"]);
+        assert!(package.manifest.is_complete());
+
+        let b_bytes = read_entry(&package.archive, None, "xrefs/B.cdyj").unwrap();
+        let b = JsonFormat::new()
+            .from_string(std::str::from_utf8(&b_bytes).unwrap())
+            .unwrap();
+        assert_eq!(b.entities[0].attributes[XREF_ATTRIBUTE], "../A.cdyj");
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_encrypted_package() {
+        let (dir, root) = project();
+        let mut options = TransmittalOptions::default().with_password("tender-2026");
+        options.kdf = Argon2Config::low_memory();
+        let package = Transmittal::new(options).package(&root).unwrap();
+        assert!(is_encrypted(&package.archive));
+        assert!(package.manifest.encrypted);
+
+        assert!(matches!(
+            read_manifest(&package.archive, None),
+            Err(TransmittalError::PasswordRequired)
+        ));
+        assert!(matches!(
+            read_manifest(&package.archive, Some("wrong")),
+            Err(TransmittalError::Decryption)
+        ));
+        let manifest = read_manifest(&package.archive, Some("tender-2026")).unwrap();
+        assert_eq!(manifest.files.len(), package.manifest.files.len());
+
+        // The header is bound to the ciphertext
+        let mut tampered = package.archive.clone();
+        tampered[9] ^= 1;
+        assert!(decrypt_archive(&tampered, "tender-2026").is_err());
+
+        let out = dir.join("out.zip");
+        package.write(&out).unwrap();
+        assert!(fs::read_to_string(dir.join("out.txt"))
+            .unwrap()
+            .contains("Protected: yes"));
+        fs::remove_dir_all(&dir).ok();
+    }
+}
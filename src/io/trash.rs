@@ -0,0 +1,459 @@
+// CADDY - Enterprise CAD System
+// File I/O System - Recycle Bin Module
+
+//! Recycle bin for soft-deleted entities and documents
+//!
+//! Deleting through a [`RecycleBin`] takes the entity or document out of the
+//! live model and keeps it, together with who deleted it, when and why,
+//! until its retention window runs out. Until then it can be restored:
+//! entities go back into their document at their original draw-order
+//! position, documents are handed back whole.
+//!
+//! Expired items are only removed by [`RecycleBin::purge_expired`], which
+//! returns a [`PurgeRecord`] per item so the caller can log the permanent
+//! deletion. The scheduled retention job
+//! (`scheduling::trash::TrashPurgeExecutor`) does exactly that and writes
+//! the records to the compliance audit trail.
+//!
+//! ## Example
+//!
+//! ```
+//! use caddy::io::document::{Document, Entity, GeometryType, Point, Vec3};
+//! use caddy::io::trash::{RecycleBin, TrashSettings};
+//!
+//! let mut doc = Document::new();
+//! let entity = Entity::new(
+//!     GeometryType::Point(Point { position: Vec3::zero() }),
+//!     "0".to_string(),
+//! );
+//! let entity_id = entity.id;
+//! doc.add_entity(entity);
+//!
+//! let mut bin = RecycleBin::new(TrashSettings::default());
+//! let item = bin.delete_entity(&mut doc, entity_id, "alice", None).unwrap();
+//! assert!(doc.entities.is_empty());
+//!
+//! bin.restore_entity(item, &mut doc).unwrap();
+//! assert_eq!(doc.entities[0].id, entity_id);
+//! ```
+
+use crate::io::document::{Document, Entity};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Recycle bin errors
+#[derive(Error, Debug)]
+pub enum TrashError {
+    #[error("Recycle bin item not found: {0}")]
+    ItemNotFound(Uuid),
+    #[error("Entity not found: {0}")]
+    EntityNotFound(Uuid),
+    #[error("Item {0} is not a deleted entity")]
+    NotAnEntity(Uuid),
+    #[error("Item {0} is not a deleted document")]
+    NotADocument(Uuid),
+    #[error("Entity belongs to document {expected}, not {found}")]
+    WrongDocument { expected: Uuid, found: Uuid },
+}
+
+pub type TrashResult<T> = Result<T, TrashError>;
+
+/// Recycle bin settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashSettings {
+    /// Days a deleted item stays restorable before it may be purged
+    pub retention_days: u32,
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        Self { retention_days: 30 }
+    }
+}
+
+/// What was deleted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TrashedObject {
+    /// An entity, with the document it came from and its index in that
+    /// document's entity list
+    Entity {
+        document_id: Uuid,
+        index: usize,
+        entity: Box<Entity>,
+    },
+    /// A whole document
+    Document(Box<Document>),
+}
+
+/// A deleted entity or document with its deletion metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashItem {
+    /// Recycle bin item ID (not the entity or document ID)
+    pub id: Uuid,
+    /// The deleted object
+    pub object: TrashedObject,
+    /// When it was deleted
+    pub deleted_at: DateTime<Utc>,
+    /// Who deleted it
+    pub deleted_by: String,
+    /// Optional reason given at deletion
+    pub reason: Option<String>,
+    /// When it becomes eligible for purging
+    pub expires_at: DateTime<Utc>,
+}
+
+impl TrashItem {
+    /// `"entity"` or `"document"`
+    pub fn kind(&self) -> &'static str {
+        match self.object {
+            TrashedObject::Entity { .. } => "entity",
+            TrashedObject::Document(_) => "document",
+        }
+    }
+
+    /// ID of the deleted entity or document
+    pub fn object_id(&self) -> Uuid {
+        match &self.object {
+            TrashedObject::Entity { entity, .. } => entity.id,
+            TrashedObject::Document(doc) => doc.id,
+        }
+    }
+
+    /// Short description for listings: the entity type or document title
+    pub fn label(&self) -> String {
+        match &self.object {
+            TrashedObject::Entity { entity, .. } => {
+                format!("{} on {}", entity.geometry.type_name(), entity.layer)
+            }
+            TrashedObject::Document(doc) if doc.metadata.title.is_empty() => {
+                format!("Document {}", doc.id)
+            }
+            TrashedObject::Document(doc) => doc.metadata.title.clone(),
+        }
+    }
+
+    /// Whether the retention window has passed
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// Record of an item removed from the recycle bin for good
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PurgeRecord {
+    /// Recycle bin item ID
+    pub item_id: Uuid,
+    /// `"entity"` or `"document"`
+    pub kind: String,
+    /// ID of the purged entity or document
+    pub object_id: Uuid,
+    /// Owning document, for entities
+    pub document_id: Option<Uuid>,
+    /// Who originally deleted it
+    pub deleted_by: String,
+    /// When it was originally deleted
+    pub deleted_at: DateTime<Utc>,
+    /// End of its retention window
+    pub expires_at: DateTime<Utc>,
+    /// When it was purged
+    pub purged_at: DateTime<Utc>,
+}
+
+impl PurgeRecord {
+    fn new(item: &TrashItem, purged_at: DateTime<Utc>) -> Self {
+        Self {
+            item_id: item.id,
+            kind: item.kind().to_string(),
+            object_id: item.object_id(),
+            document_id: match &item.object {
+                TrashedObject::Entity { document_id, .. } => Some(*document_id),
+                TrashedObject::Document(_) => None,
+            },
+            deleted_by: item.deleted_by.clone(),
+            deleted_at: item.deleted_at,
+            expires_at: item.expires_at,
+            purged_at,
+        }
+    }
+}
+
+/// Soft-delete store for entities and documents
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecycleBin {
+    settings: TrashSettings,
+    items: Vec<TrashItem>,
+}
+
+impl RecycleBin {
+    /// Create an empty recycle bin
+    pub fn new(settings: TrashSettings) -> Self {
+        Self {
+            settings,
+            items: Vec::new(),
+        }
+    }
+
+    /// Current settings
+    pub fn settings(&self) -> &TrashSettings {
+        &self.settings
+    }
+
+    /// Change the retention window; applies to items deleted from now on
+    pub fn set_retention_days(&mut self, days: u32) {
+        self.settings.retention_days = days;
+    }
+
+    /// Move an entity from `doc` into the bin, returning the bin item ID
+    pub fn delete_entity(
+        &mut self,
+        doc: &mut Document,
+        entity_id: Uuid,
+        deleted_by: &str,
+        reason: Option<String>,
+    ) -> TrashResult<Uuid> {
+        let index = doc
+            .entities
+            .iter()
+            .position(|e| e.id == entity_id)
+            .ok_or(TrashError::EntityNotFound(entity_id))?;
+        let entity = doc
+            .remove_entity(entity_id)
+            .ok_or(TrashError::EntityNotFound(entity_id))?;
+        doc.metadata.modified = Utc::now();
+
+        Ok(self.push(
+            TrashedObject::Entity {
+                document_id: doc.id,
+                index,
+                entity: Box::new(entity),
+            },
+            deleted_by,
+            reason,
+        ))
+    }
+
+    /// Move a whole document into the bin, returning the bin item ID
+    pub fn delete_document(
+        &mut self,
+        doc: Document,
+        deleted_by: &str,
+        reason: Option<String>,
+    ) -> Uuid {
+        self.push(TrashedObject::Document(Box::new(doc)), deleted_by, reason)
+    }
+
+    /// Put a deleted entity back into its document, returning the entity ID
+    pub fn restore_entity(&mut self, item_id: Uuid, doc: &mut Document) -> TrashResult<Uuid> {
+        match &self.get(item_id).ok_or(TrashError::ItemNotFound(item_id))?.object {
+            TrashedObject::Entity { document_id, .. } if *document_id != doc.id => {
+                return Err(TrashError::WrongDocument {
+                    expected: *document_id,
+                    found: doc.id,
+                })
+            }
+            TrashedObject::Entity { .. } => {}
+            TrashedObject::Document(_) => return Err(TrashError::NotAnEntity(item_id)),
+        }
+
+        match self.take(item_id)?.object {
+            TrashedObject::Entity { index, entity, .. } => {
+                let id = doc.insert_entity(index, *entity);
+                doc.metadata.modified = Utc::now();
+                Ok(id)
+            }
+            TrashedObject::Document(_) => unreachable!("checked above"),
+        }
+    }
+
+    /// Take a deleted document back out of the bin
+    pub fn restore_document(&mut self, item_id: Uuid) -> TrashResult<Document> {
+        match self.get(item_id).map(|item| &item.object) {
+            None => Err(TrashError::ItemNotFound(item_id)),
+            Some(TrashedObject::Entity { .. }) => Err(TrashError::NotADocument(item_id)),
+            Some(TrashedObject::Document(_)) => match self.take(item_id)?.object {
+                TrashedObject::Document(doc) => Ok(*doc),
+                TrashedObject::Entity { .. } => unreachable!("checked above"),
+            },
+        }
+    }
+
+    /// Remove an item from the bin without purging it, for callers that
+    /// restore into their own storage
+    pub fn take(&mut self, item_id: Uuid) -> TrashResult<TrashItem> {
+        let index = self
+            .items
+            .iter()
+            .position(|item| item.id == item_id)
+            .ok_or(TrashError::ItemNotFound(item_id))?;
+        Ok(self.items.remove(index))
+    }
+
+    /// Look up an item
+    pub fn get(&self, item_id: Uuid) -> Option<&TrashItem> {
+        self.items.iter().find(|item| item.id == item_id)
+    }
+
+    /// All items, oldest deletion first
+    pub fn items(&self) -> &[TrashItem] {
+        &self.items
+    }
+
+    /// Deleted entities belonging to one document
+    pub fn entities_of(&self, document_id: Uuid) -> impl Iterator<Item = &TrashItem> {
+        self.items.iter().filter(move |item| {
+            matches!(&item.object, TrashedObject::Entity { document_id: d, .. } if *d == document_id)
+        })
+    }
+
+    /// Number of items in the bin
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether the bin is empty
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Permanently remove every item whose retention window has passed
+    pub fn purge_expired(&mut self, now: DateTime<Utc>) -> Vec<PurgeRecord> {
+        let mut purged = Vec::new();
+        self.items.retain(|item| {
+            if item.is_expired(now) {
+                purged.push(PurgeRecord::new(item, now));
+                false
+            } else {
+                true
+            }
+        });
+        purged
+    }
+
+    fn push(&mut self, object: TrashedObject, deleted_by: &str, reason: Option<String>) -> Uuid {
+        let deleted_at = Utc::now();
+        let item = TrashItem {
+            id: Uuid::new_v4(),
+            object,
+            deleted_at,
+            deleted_by: deleted_by.to_string(),
+            reason,
+            expires_at: deleted_at + Duration::days(i64::from(self.settings.retention_days)),
+        };
+        let id = item.id;
+        self.items.push(item);
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{GeometryType, Line, Vec3};
+
+    fn line(x: f64) -> Entity {
+        Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::new(x, 0.0, 0.0),
+                end: Vec3::new(x, 10.0, 0.0),
+            }),
+            "0".to_string(),
+        )
+    }
+
+    fn document_with_lines(count: usize) -> (Document, Vec<Uuid>) {
+        let mut doc = Document::new();
+        let mut ids = Vec::new();
+        for i in 0..count {
+            let entity = line(i as f64);
+            ids.push(entity.id);
+            doc.add_entity(entity);
+        }
+        (doc, ids)
+    }
+
+    #[test]
+    fn test_entity_round_trip_keeps_draw_order() {
+        let (mut doc, ids) = document_with_lines(3);
+        let mut bin = RecycleBin::new(TrashSettings::default());
+
+        let item = bin
+            .delete_entity(&mut doc, ids[1], "alice", Some("duplicate".to_string()))
+            .unwrap();
+        assert_eq!(doc.entities.len(), 2);
+        assert_eq!(bin.entities_of(doc.id).count(), 1);
+
+        let stored = bin.get(item).unwrap();
+        assert_eq!(stored.kind(), "entity");
+        assert_eq!(stored.object_id(), ids[1]);
+        assert_eq!(stored.deleted_by, "alice");
+        assert_eq!(stored.reason.as_deref(), Some("duplicate"));
+        assert_eq!(stored.expires_at - stored.deleted_at, Duration::days(30));
+        assert_eq!(stored.label(), "Line on 0");
+
+        let mut other = Document::new();
+        assert!(matches!(
+            bin.restore_entity(item, &mut other),
+            Err(TrashError::WrongDocument { .. })
+        ));
+        assert!(matches!(bin.restore_document(item), Err(TrashError::NotADocument(_))));
+
+        let version = doc.version();
+        assert_eq!(bin.restore_entity(item, &mut doc).unwrap(), ids[1]);
+        let order: Vec<Uuid> = doc.entities.iter().map(|e| e.id).collect();
+        assert_eq!(order, ids);
+        assert!(doc.entity_revision(ids[1]) > Some(version));
+        let restored = doc.entities[1].bounding_box();
+        let found: Vec<Uuid> = doc.entities_in_box(&restored).iter().map(|e| e.id).collect();
+        assert!(found.contains(&ids[1]));
+        assert_eq!(doc.spatial_index().len(), 3);
+        assert!(bin.is_empty());
+        assert!(matches!(
+            bin.delete_entity(&mut doc, Uuid::new_v4(), "alice", None),
+            Err(TrashError::EntityNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_document_round_trip() {
+        let (mut doc, _) = document_with_lines(2);
+        doc.metadata.title = "Site plan".to_string();
+        let doc_id = doc.id;
+        let mut bin = RecycleBin::default();
+
+        let item = bin.delete_document(doc, "bob", None);
+        assert_eq!(bin.get(item).unwrap().label(), "Site plan");
+        assert!(matches!(
+            bin.restore_entity(item, &mut Document::new()),
+            Err(TrashError::NotAnEntity(_))
+        ));
+
+        let restored = bin.restore_document(item).unwrap();
+        assert_eq!(restored.id, doc_id);
+        assert_eq!(restored.entities.len(), 2);
+        assert!(matches!(bin.restore_document(item), Err(TrashError::ItemNotFound(_))));
+    }
+
+    #[test]
+    fn test_purge_expired() {
+        let (mut doc, ids) = document_with_lines(2);
+        let mut bin = RecycleBin::new(TrashSettings { retention_days: 7 });
+        let old = bin.delete_entity(&mut doc, ids[0], "alice", None).unwrap();
+        bin.set_retention_days(14);
+        let recent = bin.delete_entity(&mut doc, ids[1], "alice", None).unwrap();
+
+        let now = Utc::now();
+        assert!(bin.purge_expired(now).is_empty());
+
+        let purged = bin.purge_expired(now + Duration::days(10));
+        assert_eq!(purged.len(), 1);
+        assert_eq!(purged[0].item_id, old);
+        assert_eq!(purged[0].kind, "entity");
+        assert_eq!(purged[0].object_id, ids[0]);
+        assert_eq!(purged[0].document_id, Some(doc.id));
+        assert_eq!(purged[0].deleted_by, "alice");
+        assert!(bin.get(old).is_none());
+        assert!(bin.get(recent).is_some());
+    }
+}
@@ -55,6 +55,14 @@ impl Unit {
         in_meters * target.from_meters()
     }
 
+    /// Whether this is an inch or foot based unit
+    pub fn is_imperial(&self) -> bool {
+        matches!(
+            self,
+            Unit::Inches | Unit::Feet | Unit::Engineering | Unit::Architectural | Unit::Fractional
+        )
+    }
+
     /// Get the default precision (decimal places) for this unit
     pub fn default_precision(&self) -> usize {
         match self {
@@ -26,13 +26,14 @@
 use crate::io::document::*;
 use std::collections::{HashMap, HashSet};
 use thiserror::Error;
+use uuid::Uuid;
 
 /// Validation errors
 #[derive(Error, Debug, Clone)]
 pub enum ValidationError {
     #[error("Degenerate entity: {entity_type} at ID {entity_id}")]
     DegenerateEntity {
-        entity_id: String,
+        entity_id: Uuid,
         entity_type: String,
     },
 
@@ -49,7 +50,7 @@ pub enum ValidationError {
     },
 
     #[error("Duplicate entity ID: {0}")]
-    DuplicateId(String),
+    DuplicateId(Uuid),
 
     #[error("Empty document")]
     EmptyDocument,
@@ -65,7 +66,7 @@ pub enum ValidationError {
 }
 
 /// Validation severity level
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Severity {
     Info,
     Warning,
@@ -79,7 +80,7 @@ pub struct ValidationIssue {
     pub severity: Severity,
     pub error: ValidationError,
     pub repairable: bool,
-    pub entity_id: Option<String>,
+    pub entity_id: Option<Uuid>,
 }
 
 impl ValidationIssue {
@@ -92,7 +93,7 @@ impl ValidationIssue {
         }
     }
 
-    fn with_entity_id(mut self, id: String) -> Self {
+    fn with_entity_id(mut self, id: Uuid) -> Self {
         self.entity_id = Some(id);
         self
     }
@@ -216,12 +217,12 @@ impl Validator {
                             ValidationIssue::new(
                                 Severity::Warning,
                                 ValidationError::DegenerateEntity {
-                                    entity_id: entity.id.clone(),
+                                    entity_id: entity.id,
                                     entity_type: "Line".to_string(),
                                 },
                                 true,
                             )
-                            .with_entity_id(entity.id.clone()),
+                            .with_entity_id(entity.id),
                         );
                     }
                 }
@@ -232,12 +233,12 @@ impl Validator {
                             ValidationIssue::new(
                                 Severity::Warning,
                                 ValidationError::DegenerateEntity {
-                                    entity_id: entity.id.clone(),
+                                    entity_id: entity.id,
                                     entity_type: "Circle".to_string(),
                                 },
                                 true,
                             )
-                            .with_entity_id(entity.id.clone()),
+                            .with_entity_id(entity.id),
                         );
                     }
                 }
@@ -248,12 +249,12 @@ impl Validator {
                             ValidationIssue::new(
                                 Severity::Warning,
                                 ValidationError::DegenerateEntity {
-                                    entity_id: entity.id.clone(),
+                                    entity_id: entity.id,
                                     entity_type: "Arc".to_string(),
                                 },
                                 true,
                             )
-                            .with_entity_id(entity.id.clone()),
+                            .with_entity_id(entity.id),
                         );
                     }
 
@@ -264,28 +265,28 @@ impl Validator {
                             ValidationIssue::new(
                                 Severity::Warning,
                                 ValidationError::DegenerateEntity {
-                                    entity_id: entity.id.clone(),
+                                    entity_id: entity.id,
                                     entity_type: "Arc (zero sweep)".to_string(),
                                 },
                                 true,
                             )
-                            .with_entity_id(entity.id.clone()),
+                            .with_entity_id(entity.id),
                         );
                     }
                 }
                 GeometryType::Polyline(polyline) => {
                     // Check for polylines with fewer than 2 points
-                    if polyline.points.len() < 2 {
+                    if polyline.vertices.len() < 2 {
                         issues.push(
                             ValidationIssue::new(
                                 Severity::Error,
                                 ValidationError::DegenerateEntity {
-                                    entity_id: entity.id.clone(),
+                                    entity_id: entity.id,
                                     entity_type: "Polyline (insufficient points)".to_string(),
                                 },
                                 true,
                             )
-                            .with_entity_id(entity.id.clone()),
+                            .with_entity_id(entity.id),
                         );
                     }
                 }
@@ -300,10 +301,10 @@ impl Validator {
         let mut issues = Vec::new();
 
         // Build set of valid layer names
-        let layer_names: HashSet<String> = doc.layers.iter().map(|l| l.name.clone()).collect();
+        let layer_names: HashSet<String> = doc.layers.keys().cloned().collect();
 
         // Build set of valid block names
-        let block_names: HashSet<String> = doc.blocks.iter().map(|b| b.name.clone()).collect();
+        let block_names: HashSet<String> = doc.blocks.keys().cloned().collect();
 
         // Check entity layer references
         for entity in &doc.entities {
@@ -317,7 +318,7 @@ impl Validator {
                         },
                         true,
                     )
-                    .with_entity_id(entity.id.clone()),
+                    .with_entity_id(entity.id),
                 );
             }
 
@@ -333,7 +334,7 @@ impl Validator {
                             },
                             false,
                         )
-                        .with_entity_id(entity.id.clone()),
+                        .with_entity_id(entity.id),
                     );
                 }
             }
@@ -347,14 +348,14 @@ impl Validator {
         let mut seen_ids = HashSet::new();
 
         for entity in &doc.entities {
-            if !seen_ids.insert(entity.id.clone()) {
+            if !seen_ids.insert(entity.id) {
                 issues.push(
                     ValidationIssue::new(
                         Severity::Error,
-                        ValidationError::DuplicateId(entity.id.clone()),
+                        ValidationError::DuplicateId(entity.id),
                         true,
                     )
-                    .with_entity_id(entity.id.clone()),
+                    .with_entity_id(entity.id),
                 );
             }
         }
@@ -379,7 +380,7 @@ impl Validator {
                                 },
                                 false,
                             )
-                            .with_entity_id(entity.id.clone()),
+                            .with_entity_id(entity.id),
                         );
                     }
                 }
@@ -394,7 +395,7 @@ impl Validator {
                                 },
                                 false,
                             )
-                            .with_entity_id(entity.id.clone()),
+                            .with_entity_id(entity.id),
                         );
                     }
                 }
@@ -409,7 +410,7 @@ impl Validator {
                                 },
                                 false,
                             )
-                            .with_entity_id(entity.id.clone()),
+                            .with_entity_id(entity.id),
                         );
                     }
 
@@ -423,7 +424,7 @@ impl Validator {
                                 },
                                 false,
                             )
-                            .with_entity_id(entity.id.clone()),
+                            .with_entity_id(entity.id),
                         );
                     }
                 }
@@ -485,23 +486,24 @@ impl Repairer {
             match &issue.error {
                 ValidationError::DegenerateEntity { entity_id, .. } => {
                     if self.remove_degenerate {
-                        entities_to_remove.insert(entity_id.clone());
+                        entities_to_remove.insert(*entity_id);
                         repair_count += 1;
                     }
                 }
                 ValidationError::InvalidReference { reference_type, reference } => {
                     if self.fix_references && reference_type == "Layer" {
                         // Create missing layer
-                        if !doc.layers.iter().any(|l| &l.name == reference) {
-                            doc.layers.push(Layer {
+                        if !doc.layers.contains_key(reference) {
+                            doc.layers.insert(reference.clone(), Layer {
                                 name: reference.clone(),
                                 color: Color::white(),
-                                line_type: "Continuous".to_string(),
+                                line_type: LineType::Continuous,
                                 line_weight: LineWeight::Default,
                                 visible: true,
                                 locked: false,
                                 frozen: false,
                                 plottable: true,
+                                confidential: false,
                             });
                             repair_count += 1;
                         }
@@ -510,7 +512,7 @@ impl Repairer {
                 ValidationError::DuplicateId(id) => {
                     if self.remove_duplicates {
                         // Keep first occurrence, remove duplicates
-                        entities_to_remove.insert(id.clone());
+                        entities_to_remove.insert(*id);
                         repair_count += 1;
                     }
                 }
@@ -609,7 +611,7 @@ mod tests {
     #[test]
     fn test_empty_document_validation() {
         let validator = Validator::new();
-        let _doc = Document::new();
+        let doc = Document::new();
 
         let result = validator.validate(&doc);
         assert!(result.is_err());
@@ -0,0 +1,404 @@
+// CADDY - Enterprise CAD System
+// File I/O System - Workspace Encryption Module
+
+//! Encryption at rest for the local workspace
+//!
+//! Cached copies of cloud documents and autosave files are sealed with
+//! AES-256-GCM so a lost or stolen laptop does not leak the drawings on it.
+//! The key never touches the disk: each user has a random secret in the OS
+//! keychain, and the workspace key is derived from it with Argon2id using a
+//! salt kept next to the workspace. Without the keychain entry, which the
+//! OS only unlocks for the logged-in user, the sealed files are noise.
+//!
+//! Every sealed file starts with a short header naming the key it was
+//! sealed with, and the header and the file's name are authenticated along
+//! with the contents, so files cannot be swapped or renamed into one
+//! another. Opening is transparent: [`WorkspaceVault::read`] and
+//! [`FormatDetector::load_with_vault`](crate::io::native::FormatDetector::load_with_vault)
+//! decrypt sealed files and pass plain ones through.
+//!
+//! The platform keychain (macOS Keychain, Windows Credential Manager,
+//! Secret Service) is reached through the [`Keychain`] trait, implemented
+//! by the desktop shell; [`MemoryKeychain`] serves tests and headless
+//! runs.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use caddy::io::vault::{MemoryKeychain, WorkspaceVault};
+//!
+//! let keychain = MemoryKeychain::new();
+//! let vault = WorkspaceVault::open(&keychain, "alice", "workspace".as_ref()).unwrap();
+//!
+//! vault.write("workspace/autosave/plan.cdy".as_ref(), b"drawing bytes").unwrap();
+//! let data = vault.read("workspace/autosave/plan.cdy".as_ref()).unwrap();
+//! assert_eq!(data, b"drawing bytes");
+//! ```
+
+use crate::enterprise::crypto::kdf::{Argon2Config, KdfProvider};
+use crate::enterprise::crypto::symmetric::{Aes256GcmCipher, EncryptedData};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use thiserror::Error;
+
+/// Keychain service name the workspace secrets are stored under
+pub const KEYCHAIN_SERVICE: &str = "caddy.workspace";
+
+/// Leading bytes of every sealed file
+const MAGIC: &[u8; 4] = b"CDYV";
+/// Sealed file format version
+const VERSION: u8 = 1;
+/// Bytes identifying the key a file was sealed with
+const KEY_ID_SIZE: usize = 8;
+/// Magic, version and key id
+const HEADER_SIZE: usize = 4 + 1 + KEY_ID_SIZE;
+/// Keychain secret length
+const SECRET_SIZE: usize = 32;
+/// Argon2id salt length
+const SALT_SIZE: usize = 16;
+
+/// Workspace encryption errors
+#[derive(Error, Debug)]
+pub enum VaultError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Keychain error: {0}")]
+    Keychain(String),
+    #[error("Key derivation failed: {0}")]
+    KeyDerivation(String),
+    #[error("Invalid key info: {0}")]
+    KeyInfo(String),
+    #[error("The keychain secret no longer matches this workspace")]
+    KeyMismatch,
+    #[error("Data is not sealed")]
+    NotSealed,
+    #[error("Sealed with a different workspace key")]
+    WrongKey,
+    #[error("Wrong key or corrupted data")]
+    Decryption,
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+}
+
+pub type VaultResult<T> = Result<T, VaultError>;
+
+/// Secret storage provided by the operating system
+pub trait Keychain: Send + Sync {
+    /// Secret stored for `account` under `service`, if any
+    fn get(&self, service: &str, account: &str) -> VaultResult<Option<Vec<u8>>>;
+
+    /// Store or replace the secret for `account` under `service`
+    fn set(&self, service: &str, account: &str, secret: &[u8]) -> VaultResult<()>;
+}
+
+/// Keychain held in process memory, for tests and headless runs
+#[derive(Debug, Default)]
+pub struct MemoryKeychain {
+    entries: Mutex<HashMap<(String, String), Vec<u8>>>,
+}
+
+impl MemoryKeychain {
+    /// Create an empty keychain
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Keychain for MemoryKeychain {
+    fn get(&self, service: &str, account: &str) -> VaultResult<Option<Vec<u8>>> {
+        let entries = self.entries.lock().map_err(|e| VaultError::Keychain(e.to_string()))?;
+        Ok(entries.get(&(service.to_string(), account.to_string())).cloned())
+    }
+
+    fn set(&self, service: &str, account: &str, secret: &[u8]) -> VaultResult<()> {
+        let mut entries = self.entries.lock().map_err(|e| VaultError::Keychain(e.to_string()))?;
+        entries.insert((service.to_string(), account.to_string()), secret.to_vec());
+        Ok(())
+    }
+}
+
+/// Key derivation settings kept in the workspace, next to the files they
+/// protect. Holds nothing secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyInfo {
+    version: u8,
+    memory_cost: u32,
+    time_cost: u32,
+    parallelism: u32,
+    /// Argon2id salt, hex encoded
+    salt: String,
+    /// Id of the derived key, hex encoded; detects a replaced keychain entry
+    key_id: String,
+}
+
+impl KeyInfo {
+    fn kdf(&self) -> Argon2Config {
+        Argon2Config {
+            memory_cost: self.memory_cost,
+            time_cost: self.time_cost,
+            parallelism: self.parallelism,
+            ..Argon2Config::default()
+        }
+    }
+}
+
+/// Seals and opens files in a user's local workspace
+pub struct WorkspaceVault {
+    cipher: Aes256GcmCipher,
+    key_id: [u8; KEY_ID_SIZE],
+}
+
+impl WorkspaceVault {
+    /// Open the vault for `user`'s workspace at `workspace`, creating the
+    /// keychain secret and key info on first use
+    pub fn open(keychain: &dyn Keychain, user: &str, workspace: &Path) -> VaultResult<Self> {
+        Self::open_with(keychain, user, workspace, &Argon2Config::default())
+    }
+
+    /// Open the vault, deriving a new workspace's key with `kdf`. An existing
+    /// workspace keeps the costs it was created with.
+    pub fn open_with(keychain: &dyn Keychain, user: &str, workspace: &Path, kdf: &Argon2Config) -> VaultResult<Self> {
+        let secret = match keychain.get(KEYCHAIN_SERVICE, user)? {
+            Some(secret) => secret,
+            None => {
+                let mut secret = vec![0u8; SECRET_SIZE];
+                OsRng.fill_bytes(&mut secret);
+                keychain.set(KEYCHAIN_SERVICE, user, &secret)?;
+                secret
+            }
+        };
+
+        let info_path = key_info_path(workspace, user);
+        if info_path.exists() {
+            let info: KeyInfo =
+                serde_json::from_slice(&std::fs::read(&info_path)?).map_err(|e| VaultError::KeyInfo(e.to_string()))?;
+            let salt = hex::decode(&info.salt).map_err(|e| VaultError::KeyInfo(e.to_string()))?;
+            let vault = Self::from_secret(&secret, &salt, &info.kdf())?;
+            if hex::encode(vault.key_id) != info.key_id {
+                return Err(VaultError::KeyMismatch);
+            }
+            return Ok(vault);
+        }
+
+        let mut salt = [0u8; SALT_SIZE];
+        OsRng.fill_bytes(&mut salt);
+        let vault = Self::from_secret(&secret, &salt, kdf)?;
+        let info = KeyInfo {
+            version: VERSION,
+            memory_cost: kdf.memory_cost,
+            time_cost: kdf.time_cost,
+            parallelism: kdf.parallelism,
+            salt: hex::encode(salt),
+            key_id: hex::encode(vault.key_id),
+        };
+        std::fs::create_dir_all(workspace)?;
+        let json = serde_json::to_vec_pretty(&info).map_err(|e| VaultError::KeyInfo(e.to_string()))?;
+        write_atomic(&info_path, &json)?;
+        Ok(vault)
+    }
+
+    /// Vault keyed by Argon2id over a secret and salt
+    pub fn from_secret(secret: &[u8], salt: &[u8], kdf: &Argon2Config) -> VaultResult<Self> {
+        let kdf = Argon2Config {
+            key_length: Aes256GcmCipher::KEY_SIZE,
+            ..kdf.clone()
+        };
+        let key =
+            KdfProvider::derive_argon2id(secret, salt, &kdf).map_err(|e| VaultError::KeyDerivation(e.to_string()))?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(b"caddy-workspace-key-id");
+        hasher.update(key.as_bytes());
+        let mut key_id = [0u8; KEY_ID_SIZE];
+        key_id.copy_from_slice(&hasher.finalize()[..KEY_ID_SIZE]);
+
+        let cipher = Aes256GcmCipher::new(key.as_bytes()).map_err(|e| VaultError::Encryption(e.to_string()))?;
+        Ok(Self { cipher, key_id })
+    }
+
+    /// Whether `data` is a sealed file
+    pub fn is_sealed(data: &[u8]) -> bool {
+        data.len() >= HEADER_SIZE && &data[..4] == MAGIC
+    }
+
+    /// Whether the file at `path` is sealed
+    pub fn is_sealed_file(path: &Path) -> VaultResult<bool> {
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+        std::fs::File::open(path)?
+            .take(HEADER_SIZE as u64)
+            .read_to_end(&mut header)?;
+        Ok(Self::is_sealed(&header))
+    }
+
+    /// Encrypt `plaintext`, binding it to `name` (a file name or cache key)
+    pub fn seal(&self, name: &str, plaintext: &[u8]) -> VaultResult<Vec<u8>> {
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+        header.extend_from_slice(MAGIC);
+        header.push(VERSION);
+        header.extend_from_slice(&self.key_id);
+
+        let encrypted = self
+            .cipher
+            .encrypt(plaintext, Some(&associated_data(&header, name)))
+            .map_err(|e| VaultError::Encryption(e.to_string()))?;
+
+        let mut sealed = header;
+        sealed.extend_from_slice(&encrypted.to_bytes());
+        Ok(sealed)
+    }
+
+    /// Decrypt data sealed under `name`
+    pub fn unseal(&self, name: &str, sealed: &[u8]) -> VaultResult<Vec<u8>> {
+        if !Self::is_sealed(sealed) {
+            return Err(VaultError::NotSealed);
+        }
+        let (header, body) = sealed.split_at(HEADER_SIZE);
+        if header[4] != VERSION {
+            return Err(VaultError::Decryption);
+        }
+        if header[5..] != self.key_id {
+            return Err(VaultError::WrongKey);
+        }
+
+        let mut encrypted =
+            EncryptedData::from_bytes(body, Aes256GcmCipher::NONCE_SIZE).map_err(|_| VaultError::Decryption)?;
+        encrypted.associated_data = associated_data(header, name);
+        self.cipher.decrypt(&encrypted).map_err(|_| VaultError::Decryption)
+    }
+
+    /// Seal `data` and write it to `path`, replacing any existing file only
+    /// once the new one is complete
+    pub fn write(&self, path: &Path, data: &[u8]) -> VaultResult<()> {
+        let sealed = self.seal(&file_name(path), data)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        write_atomic(path, &sealed)?;
+        Ok(())
+    }
+
+    /// Read a file, decrypting it if it is sealed. Plain files, such as
+    /// autosaves from before encryption was enabled, are returned as is.
+    pub fn read(&self, path: &Path) -> VaultResult<Vec<u8>> {
+        let data = std::fs::read(path)?;
+        if Self::is_sealed(&data) {
+            self.unseal(&file_name(path), &data)
+        } else {
+            Ok(data)
+        }
+    }
+}
+
+impl std::fmt::Debug for WorkspaceVault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WorkspaceVault")
+            .field("key_id", &hex::encode(self.key_id))
+            .finish()
+    }
+}
+
+fn key_info_path(workspace: &Path, user: &str) -> PathBuf {
+    let user: String = user
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    workspace.join(format!(".vault-{}.json", user))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+fn associated_data(header: &[u8], name: &str) -> Vec<u8> {
+    let mut aad = header.to_vec();
+    aad.extend_from_slice(name.as_bytes());
+    aad
+}
+
+/// Write to a sibling temporary file and rename it over `path`, so a crash
+/// mid-write never leaves a truncated file behind
+fn write_atomic(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    std::fs::write(&tmp, data)?;
+    std::fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast_kdf() -> Argon2Config {
+        Argon2Config {
+            memory_cost: 64,
+            time_cost: 1,
+            parallelism: 1,
+            key_length: 32,
+        }
+    }
+
+    #[test]
+    fn test_seal_binds_contents_to_name() {
+        let vault = WorkspaceVault::from_secret(&[7u8; 32], &[1u8; 16], &fast_kdf()).unwrap();
+        let sealed = vault.seal("plan.cdy", b"confidential").unwrap();
+
+        assert!(WorkspaceVault::is_sealed(&sealed));
+        assert!(!sealed.windows(12).any(|w| w == b"confidential"));
+        assert_eq!(vault.unseal("plan.cdy", &sealed).unwrap(), b"confidential");
+        assert!(matches!(
+            vault.unseal("other.cdy", &sealed),
+            Err(VaultError::Decryption)
+        ));
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            vault.unseal("plan.cdy", &tampered),
+            Err(VaultError::Decryption)
+        ));
+
+        let other = WorkspaceVault::from_secret(&[8u8; 32], &[1u8; 16], &fast_kdf()).unwrap();
+        assert!(matches!(other.unseal("plan.cdy", &sealed), Err(VaultError::WrongKey)));
+    }
+
+    #[test]
+    fn test_workspace_key_survives_reopen() {
+        let dir = std::env::temp_dir().join(format!("caddy-vault-{}", uuid::Uuid::new_v4()));
+        let keychain = MemoryKeychain::new();
+
+        let vault = WorkspaceVault::open_with(&keychain, "alice", &dir, &fast_kdf()).unwrap();
+        let path = dir.join("autosave").join("plan.cdy");
+        vault.write(&path, b"drawing").unwrap();
+        assert!(WorkspaceVault::is_sealed(&std::fs::read(&path).unwrap()));
+        drop(vault);
+
+        let reopened = WorkspaceVault::open(&keychain, "alice", &dir).unwrap();
+        assert_eq!(reopened.read(&path).unwrap(), b"drawing");
+
+        // Plain files pass through
+        let plain = dir.join("legacy.cdy");
+        std::fs::write(&plain, b"CDDY").unwrap();
+        assert_eq!(reopened.read(&plain).unwrap(), b"CDDY");
+
+        // A replaced keychain secret is reported rather than misread
+        keychain.set(KEYCHAIN_SERVICE, "alice", &[0u8; 32]).unwrap();
+        assert!(matches!(
+            WorkspaceVault::open(&keychain, "alice", &dir),
+            Err(VaultError::KeyMismatch)
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
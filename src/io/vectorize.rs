@@ -0,0 +1,602 @@
+//! Raster tracing for image import
+//!
+//! Scanned linework goes through three stages: a threshold turns the image
+//! into an ink [`Bitmap`], a despeckle pass drops scanner noise, and the ink
+//! is traced either as region outlines or as centerlines. Outline tracing
+//! follows pixel edges, so a pen stroke becomes a thin closed loop around
+//! the stroke. Centerline tracing thins the ink to a one-pixel skeleton
+//! first and follows that, so a stroke becomes a single open path, which is
+//! what drafters expect from a scanned drawing.
+//!
+//! Colored originals can be split with [`separate_colors`] so that each ink
+//! color is thresholded and traced on its own.
+//!
+//! All coordinates are in pixels with the origin at the top-left corner of
+//! the image and y pointing down; the importer flips and scales them.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::geometry::Point2D;
+
+/// Number of k-means passes used for color separation
+const COLOR_ITERATIONS: usize = 12;
+
+/// An RGB image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RasterImage {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Pixels in row-major order
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl RasterImage {
+    /// Build an image from packed RGB bytes
+    ///
+    /// Returns `None` if `rgb` is not `width * height * 3` bytes long.
+    pub fn from_rgb(width: u32, height: u32, rgb: &[u8]) -> Option<Self> {
+        if rgb.len() != width as usize * height as usize * 3 {
+            return None;
+        }
+        let pixels = rgb.chunks_exact(3).map(|p| [p[0], p[1], p[2]]).collect();
+        Some(Self { width, height, pixels })
+    }
+
+    /// Build an image by evaluating `f(x, y)` for every pixel
+    pub fn from_fn(width: u32, height: u32, mut f: impl FnMut(u32, u32) -> [u8; 3]) -> Self {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Self { width, height, pixels }
+    }
+
+    /// Pixel at `(x, y)`
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        self.pixels[(y * self.width + x) as usize]
+    }
+}
+
+/// Perceived brightness (ITU-R BT.601)
+fn luma([r, g, b]: [u8; 3]) -> u8 {
+    ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000) as u8
+}
+
+/// A one-bit image where `true` marks ink
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitmap {
+    width: u32,
+    height: u32,
+    bits: Vec<bool>,
+}
+
+impl Bitmap {
+    /// Create an empty bitmap
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            bits: vec![false; width as usize * height as usize],
+        }
+    }
+
+    /// Mark pixels darker than `threshold` as ink
+    pub fn threshold(image: &RasterImage, threshold: u8) -> Self {
+        Self {
+            width: image.width,
+            height: image.height,
+            bits: image.pixels.iter().map(|&p| luma(p) < threshold).collect(),
+        }
+    }
+
+    /// Width in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Whether `(x, y)` is ink; pixels outside the bitmap are paper
+    pub fn get(&self, x: i64, y: i64) -> bool {
+        x >= 0
+            && y >= 0
+            && x < self.width as i64
+            && y < self.height as i64
+            && self.bits[(y * self.width as i64 + x) as usize]
+    }
+
+    /// Set the pixel at `(x, y)`
+    pub fn set(&mut self, x: u32, y: u32, ink: bool) {
+        self.bits[(y * self.width + x) as usize] = ink;
+    }
+
+    /// Number of ink pixels
+    pub fn ink_pixels(&self) -> usize {
+        self.bits.iter().filter(|&&b| b).count()
+    }
+
+    /// Remove ink specks and fill paper pinholes smaller than `min_area`
+    /// pixels
+    ///
+    /// Ink is grouped 8-connected and paper 4-connected, so a diagonal
+    /// line never counts as a gap. Paper regions touching the border are
+    /// never filled. Returns the number of regions removed.
+    pub fn despeckle(&mut self, min_area: usize) -> usize {
+        if min_area <= 1 {
+            return 0;
+        }
+        let mut removed = 0;
+        for ink in [true, false] {
+            let mut seen = vec![false; self.bits.len()];
+            for start in 0..self.bits.len() {
+                if seen[start] || self.bits[start] != ink {
+                    continue;
+                }
+                let (region, touches_border) = self.flood(start, ink, &mut seen);
+                if region.len() < min_area && (ink || !touches_border) {
+                    for i in region {
+                        self.bits[i] = !ink;
+                    }
+                    removed += 1;
+                }
+            }
+        }
+        removed
+    }
+
+    /// Collect the region containing `start`
+    fn flood(&self, start: usize, ink: bool, seen: &mut [bool]) -> (Vec<usize>, bool) {
+        let w = self.width as i64;
+        let h = self.height as i64;
+        let offsets: &[(i64, i64)] = if ink {
+            &[(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)]
+        } else {
+            &[(0, -1), (-1, 0), (1, 0), (0, 1)]
+        };
+
+        let mut region = Vec::new();
+        let mut touches_border = false;
+        let mut stack = vec![start];
+        seen[start] = true;
+        while let Some(i) = stack.pop() {
+            region.push(i);
+            let (x, y) = (i as i64 % w, i as i64 / w);
+            if x == 0 || y == 0 || x == w - 1 || y == h - 1 {
+                touches_border = true;
+            }
+            for (dx, dy) in offsets {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= w || ny >= h {
+                    continue;
+                }
+                let n = (ny * w + nx) as usize;
+                if !seen[n] && self.bits[n] == ink {
+                    seen[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
+        (region, touches_border)
+    }
+
+    /// Thin the ink to a one-pixel-wide skeleton (Zhang-Suen)
+    pub fn skeletonize(&self) -> Bitmap {
+        let mut skeleton = self.clone();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for pass in 0..2 {
+                let mut clear = Vec::new();
+                for y in 0..self.height as i64 {
+                    for x in 0..self.width as i64 {
+                        if skeleton.get(x, y) && skeleton.thinnable(x, y, pass) {
+                            clear.push((x as u32, y as u32));
+                        }
+                    }
+                }
+                changed |= !clear.is_empty();
+                for (x, y) in clear {
+                    skeleton.set(x, y, false);
+                }
+            }
+        }
+        skeleton
+    }
+
+    /// Zhang-Suen deletion test for one sub-iteration
+    fn thinnable(&self, x: i64, y: i64, pass: usize) -> bool {
+        // P2..P9, clockwise from north
+        let p = [
+            self.get(x, y - 1),
+            self.get(x + 1, y - 1),
+            self.get(x + 1, y),
+            self.get(x + 1, y + 1),
+            self.get(x, y + 1),
+            self.get(x - 1, y + 1),
+            self.get(x - 1, y),
+            self.get(x - 1, y - 1),
+        ];
+        let neighbors = p.iter().filter(|&&b| b).count();
+        let transitions = (0..8).filter(|&i| !p[i] && p[(i + 1) % 8]).count();
+        let (n, e, s, w) = (p[0], p[2], p[4], p[6]);
+        let sides = if pass == 0 {
+            !(e && s && (n || w))
+        } else {
+            !(n && w && (e || s))
+        };
+        (2..=6).contains(&neighbors) && transitions == 1 && sides
+    }
+
+    /// Grayscale preview with ink black on white paper, as packed RGB
+    pub fn to_rgb(&self) -> Vec<u8> {
+        self.bits
+            .iter()
+            .flat_map(|&ink| if ink { [0, 0, 0] } else { [255, 255, 255] })
+            .collect()
+    }
+}
+
+/// How ink is turned into geometry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TraceMode {
+    /// Closed boundaries around each ink region
+    #[default]
+    Outline,
+    /// Open paths along the middle of each stroke
+    Centerline,
+}
+
+/// A traced path in pixel coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub struct TracedPath {
+    /// Path vertices
+    pub points: Vec<Point2D>,
+    /// Whether the last point joins back to the first
+    pub closed: bool,
+}
+
+/// Trace the boundary of every ink region
+///
+/// Paths run along pixel edges, so vertices fall on pixel corners. Holes
+/// are traced as separate loops wound the opposite way to their outer
+/// boundary. Runs of collinear edges are merged.
+pub fn trace_outlines(bitmap: &Bitmap) -> Vec<TracedPath> {
+    // Directed pixel edges with ink on the right, keyed by start corner
+    let mut edges: Vec<((i64, i64), (i64, i64))> = Vec::new();
+    for y in 0..bitmap.height as i64 {
+        for x in 0..bitmap.width as i64 {
+            if !bitmap.get(x, y) {
+                continue;
+            }
+            if !bitmap.get(x, y - 1) {
+                edges.push(((x, y), (x + 1, y)));
+            }
+            if !bitmap.get(x + 1, y) {
+                edges.push(((x + 1, y), (x + 1, y + 1)));
+            }
+            if !bitmap.get(x, y + 1) {
+                edges.push(((x + 1, y + 1), (x, y + 1)));
+            }
+            if !bitmap.get(x - 1, y) {
+                edges.push(((x, y + 1), (x, y)));
+            }
+        }
+    }
+    let mut outgoing: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, (start, _)) in edges.iter().enumerate() {
+        outgoing.entry(*start).or_default().push(i);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut paths = Vec::new();
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let mut corners = vec![edges[first].0];
+        let mut current = first;
+        loop {
+            let (from, at) = edges[current];
+            let direction = (at.0 - from.0, at.1 - from.1);
+            // At a saddle corner, turn towards the ink so diagonally
+            // touching pixels stay separate regions
+            let next = outgoing[&at]
+                .iter()
+                .copied()
+                .filter(|&e| !used[e] || e == first)
+                .max_by_key(|&e| {
+                    let (_, to) = edges[e];
+                    direction.0 * (to.1 - at.1) - direction.1 * (to.0 - at.0)
+                });
+            match next {
+                Some(e) if e != first => {
+                    used[e] = true;
+                    corners.push(at);
+                    current = e;
+                }
+                _ => break,
+            }
+        }
+        paths.push(TracedPath {
+            points: merge_collinear(&corners),
+            closed: true,
+        });
+    }
+    paths
+}
+
+/// Drop corners that lie on a straight run of a closed loop
+fn merge_collinear(corners: &[(i64, i64)]) -> Vec<Point2D> {
+    let n = corners.len();
+    (0..n)
+        .filter(|&i| {
+            let (a, b, c) = (corners[(i + n - 1) % n], corners[i], corners[(i + 1) % n]);
+            (b.0 - a.0) * (c.1 - b.1) != (b.1 - a.1) * (c.0 - b.0)
+        })
+        .map(|i| Point2D::new(corners[i].0 as f64, corners[i].1 as f64))
+        .collect()
+}
+
+/// Trace a one-pixel skeleton into paths through pixel centers
+///
+/// Paths break at line ends and junctions; rings with no junction come
+/// back as closed paths. Isolated pixels are ignored.
+pub fn trace_centerlines(skeleton: &Bitmap) -> Vec<TracedPath> {
+    let center = |(x, y): (i64, i64)| Point2D::new(x as f64 + 0.5, y as f64 + 0.5);
+    let key = |a: (i64, i64), b: (i64, i64)| if a < b { (a, b) } else { (b, a) };
+    let mut visited: HashSet<((i64, i64), (i64, i64))> = HashSet::new();
+    let mut paths = Vec::new();
+
+    let pixels: Vec<(i64, i64)> = (0..skeleton.height as i64)
+        .flat_map(|y| (0..skeleton.width as i64).map(move |x| (x, y)))
+        .filter(|&(x, y)| skeleton.get(x, y))
+        .collect();
+
+    // Open paths start at ends and junctions
+    for &node in &pixels {
+        if skeleton_neighbors(skeleton, node).len() == 2 {
+            continue;
+        }
+        for first in skeleton_neighbors(skeleton, node) {
+            if !visited.insert(key(node, first)) {
+                continue;
+            }
+            let mut points = vec![center(node)];
+            let (mut prev, mut current) = (node, first);
+            loop {
+                points.push(center(current));
+                let neighbors = skeleton_neighbors(skeleton, current);
+                if neighbors.len() != 2 {
+                    break;
+                }
+                let next = neighbors[if neighbors[0] == prev { 1 } else { 0 }];
+                if !visited.insert(key(current, next)) {
+                    break;
+                }
+                prev = current;
+                current = next;
+            }
+            paths.push(TracedPath { points, closed: false });
+        }
+    }
+
+    // Whatever is left is a ring
+    for &start in &pixels {
+        let neighbors = skeleton_neighbors(skeleton, start);
+        if neighbors.len() != 2 || visited.contains(&key(start, neighbors[0])) {
+            continue;
+        }
+        visited.insert(key(start, neighbors[0]));
+        let mut points = vec![center(start)];
+        let (mut prev, mut current) = (start, neighbors[0]);
+        while current != start {
+            points.push(center(current));
+            let neighbors = skeleton_neighbors(skeleton, current);
+            let next = neighbors[if neighbors[0] == prev { 1 } else { 0 }];
+            visited.insert(key(current, next));
+            prev = current;
+            current = next;
+        }
+        paths.push(TracedPath { points, closed: true });
+    }
+    paths
+}
+
+/// Skeleton neighbors of a pixel
+///
+/// A diagonal neighbor only counts when neither pixel sharing an edge with
+/// both is set; otherwise staircase steps would read as junctions.
+fn skeleton_neighbors(skeleton: &Bitmap, (x, y): (i64, i64)) -> Vec<(i64, i64)> {
+    let mut neighbors: Vec<(i64, i64)> = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+        .iter()
+        .map(|(dx, dy)| (x + dx, y + dy))
+        .filter(|&(nx, ny)| skeleton.get(nx, ny))
+        .collect();
+    for (dx, dy) in [(1, -1), (1, 1), (-1, 1), (-1, -1)] {
+        if skeleton.get(x + dx, y + dy) && !skeleton.get(x + dx, y) && !skeleton.get(x, y + dy) {
+            neighbors.push((x + dx, y + dy));
+        }
+    }
+    neighbors
+}
+
+/// One ink color split out of an image
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorLayer {
+    /// Representative color of the ink
+    pub color: [u8; 3],
+    /// Pixels closest to this color
+    pub bitmap: Bitmap,
+}
+
+/// Split an image into up to `colors` ink layers
+///
+/// Pixels are clustered by k-means into `colors + 1` groups; the largest
+/// group is taken to be the paper and dropped. Layers come back ordered by
+/// pixel count, largest first, and empty ones are omitted.
+pub fn separate_colors(image: &RasterImage, colors: usize) -> Vec<ColorLayer> {
+    if colors == 0 || image.pixels.is_empty() {
+        return Vec::new();
+    }
+
+    // Cluster a 5-bit-per-channel histogram rather than every pixel
+    let mut histogram: HashMap<[u8; 3], usize> = HashMap::new();
+    for p in &image.pixels {
+        *histogram.entry([p[0] >> 3, p[1] >> 3, p[2] >> 3]).or_default() += 1;
+    }
+    let mut bins: Vec<([f64; 3], usize)> = histogram
+        .into_iter()
+        .map(|(b, n)| ([b[0] as f64 * 8.0 + 4.0, b[1] as f64 * 8.0 + 4.0, b[2] as f64 * 8.0 + 4.0], n))
+        .collect();
+    bins.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.partial_cmp(&b.0).unwrap()));
+
+    // Farthest-point seeding from the most common color
+    let k = (colors + 1).min(bins.len());
+    let mut centers = vec![bins[0].0];
+    while centers.len() < k {
+        let far = bins
+            .iter()
+            .max_by(|a, b| {
+                let da = nearest(&centers, a.0).1;
+                let db = nearest(&centers, b.0).1;
+                da.partial_cmp(&db).unwrap()
+            })
+            .unwrap();
+        centers.push(far.0);
+    }
+
+    for _ in 0..COLOR_ITERATIONS {
+        let mut sums = vec![([0.0; 3], 0usize); k];
+        for (color, count) in &bins {
+            let (i, _) = nearest(&centers, *color);
+            for (sum, channel) in sums[i].0.iter_mut().zip(color) {
+                *sum += channel * *count as f64;
+            }
+            sums[i].1 += count;
+        }
+        for (center, (sum, count)) in centers.iter_mut().zip(&sums) {
+            if *count > 0 {
+                *center = sum.map(|s| s / *count as f64);
+            }
+        }
+    }
+
+    let mut layers: Vec<(ColorLayer, usize)> = centers
+        .iter()
+        .map(|c| {
+            let color = c.map(|v| v.round().clamp(0.0, 255.0) as u8);
+            (ColorLayer { color, bitmap: Bitmap::new(image.width, image.height) }, 0)
+        })
+        .collect();
+    for (i, p) in image.pixels.iter().enumerate() {
+        let (cluster, _) = nearest(&centers, p.map(|v| v as f64));
+        layers[cluster].0.bitmap.bits[i] = true;
+        layers[cluster].1 += 1;
+    }
+
+    layers.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    layers
+        .into_iter()
+        .skip(1)
+        .filter(|(_, count)| *count > 0)
+        .map(|(layer, _)| layer)
+        .collect()
+}
+
+/// Index of and squared distance to the closest center
+fn nearest(centers: &[[f64; 3]], color: [f64; 3]) -> (usize, f64) {
+    centers
+        .iter()
+        .map(|c| (0..3).map(|i| (c[i] - color[i]).powi(2)).sum::<f64>())
+        .enumerate()
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// White image with black pixels where `f` is true
+    fn drawing(width: u32, height: u32, f: impl Fn(u32, u32) -> bool) -> RasterImage {
+        RasterImage::from_fn(width, height, |x, y| if f(x, y) { [0, 0, 0] } else { [255, 255, 255] })
+    }
+
+    #[test]
+    fn test_threshold_and_despeckle() {
+        // A 10x10 square with a 1px pinhole, plus a stray dot
+        let image = drawing(20, 20, |x, y| {
+            ((2..12).contains(&x) && (2..12).contains(&y) && (x, y) != (6, 6)) || (x, y) == (16, 16)
+        });
+        let mut bitmap = Bitmap::threshold(&image, 128);
+        assert_eq!(bitmap.ink_pixels(), 100);
+
+        assert_eq!(bitmap.despeckle(4), 2);
+        assert_eq!(bitmap.ink_pixels(), 100);
+        assert!(bitmap.get(6, 6));
+        assert!(!bitmap.get(16, 16));
+        assert_eq!(bitmap.to_rgb().len(), 20 * 20 * 3);
+    }
+
+    #[test]
+    fn test_outline_tracing() {
+        // A square ring: outer boundary plus one hole
+        let image = drawing(12, 12, |x, y| {
+            (1..11).contains(&x) && (1..11).contains(&y) && !((4..8).contains(&x) && (4..8).contains(&y))
+        });
+        let outlines = trace_outlines(&Bitmap::threshold(&image, 128));
+        assert_eq!(outlines.len(), 2);
+        assert!(outlines.iter().all(|p| p.closed && p.points.len() == 4));
+        assert!(outlines[0].points.contains(&Point2D::new(1.0, 1.0)));
+        assert!(outlines[0].points.contains(&Point2D::new(11.0, 11.0)));
+
+        // Diagonally touching pixels are separate regions
+        let image = drawing(4, 4, |x, y| (x, y) == (1, 1) || (x, y) == (2, 2));
+        assert_eq!(trace_outlines(&Bitmap::threshold(&image, 128)).len(), 2);
+    }
+
+    #[test]
+    fn test_centerline_tracing() {
+        // A 3px-thick "T": one junction and three strokes
+        let image = drawing(40, 40, |x, y| {
+            ((5..35).contains(&x) && (9..12).contains(&y)) || ((19..22).contains(&x) && (9..35).contains(&y))
+        });
+        let skeleton = Bitmap::threshold(&image, 128).skeletonize();
+        let paths = trace_centerlines(&skeleton);
+        assert_eq!(paths.len(), 3);
+        assert!(paths.iter().all(|p| !p.closed));
+        let stem = paths.iter().max_by_key(|p| p.points.len()).unwrap();
+        assert!(stem.points.iter().all(|p| (p.x - 20.5).abs() <= 1.0));
+
+        // A thick ring thins to a closed loop
+        let image = drawing(30, 30, |x, y| {
+            let r = ((x as f64 - 15.0).powi(2) + (y as f64 - 15.0).powi(2)).sqrt();
+            (8.0..11.0).contains(&r)
+        });
+        let paths = trace_centerlines(&Bitmap::threshold(&image, 128).skeletonize());
+        assert_eq!(paths.len(), 1);
+        assert!(paths[0].closed);
+    }
+
+    #[test]
+    fn test_color_separation() {
+        let image = RasterImage::from_fn(30, 10, |x, y| match (x / 10, y) {
+            (0, 2..=7) => [200, 20, 20],
+            (1, 2..=7) => [20, 20, 200],
+            _ => [250, 250, 245],
+        });
+        let layers = separate_colors(&image, 2);
+        assert_eq!(layers.len(), 2);
+        for layer in &layers {
+            assert_eq!(layer.bitmap.ink_pixels(), 60);
+        }
+        let red = layers.iter().find(|l| l.color[0] > l.color[2]).unwrap();
+        assert!(red.bitmap.get(5, 5) && !red.bitmap.get(15, 5));
+    }
+}
@@ -9,11 +9,18 @@
 //! - `rendering`: GPU-accelerated rendering pipeline
 //! - `ui`: User interface framework
 //! - `io`: File I/O for DXF and native formats
+//! - `cam`: 2.5D toolpath generation and G-code post-processing
+//! - `takeoff`: Quantity takeoff schedules with CSV/XLSX export
+//! - `sheets`: Sheet sets with title block fields and PDF/DWF publishing
+//! - `standards`: Auditing deliverables against client CAD standards
 //! - `commands`: Command system with undo/redo support
 //! - `layers`: Layer management system
 //! - `tools`: Selection and manipulation tools
 //! - `dimensions`: Dimensioning and annotations
 //! - `constraints`: Parametric constraint solver
+//! - `parts`: Standard fasteners as BOM-carrying blocks and 3D solids
+//! - `assembly`: Components from external part files positioned by mates, with
+//!   interference checks
 //! - `compression`: Enterprise-grade compression algorithms
 //! - `analytics`: Analytics and telemetry system
 //! - `database`: Enterprise database layer with caching, replication, and sharding
@@ -26,6 +33,17 @@
 //! - `teams`: Team collaboration system with workspaces, members, assignments, and activity tracking
 //! - `integrations`: CI/CD integrations for GitHub, GitLab, Jenkins, Azure DevOps, Bitbucket
 //! - `ai`: AI/ML engine with computer vision, NLP, predictions, and auto-suggestions
+//! - `wasm`: JavaScript bindings for browser builds
+//!
+//! ## Feature flags
+//!
+//! - `native` (default): everything that needs an operating system. Disabling it
+//!   leaves only `core`, `geometry`, `io`, `cam`, `takeoff`, `sheets`
+//!   (without scheduled publishing), and `standards` (without CI output),
+//!   which compile for `wasm32`.
+//! - `parallel`: multi-threaded mesh and batch processing (enabled by `native`)
+//! - `xlsx`: XLSX export of takeoff schedules (enabled by `native`)
+//! - `wasm`: the `wasm-bindgen` API in the `wasm` module
 
 #![warn(missing_docs)]
 #![warn(clippy::all)]
@@ -37,74 +55,120 @@ pub mod core;
 pub mod geometry;
 
 // Rendering system
+#[cfg(feature = "native")]
 pub mod rendering;
 
 // User interface
+#[cfg(feature = "native")]
 pub mod ui;
 
 // File I/O
 pub mod io;
 
+// CAM toolpaths and G-code
+pub mod cam;
+
+// Quantity takeoff and schedules
+pub mod takeoff;
+
+// Sheet sets and publishing
+pub mod sheets;
+
+// CAD standards auditing
+pub mod standards;
+
 // Command system
+#[cfg(feature = "native")]
 pub mod commands;
 
 // Layer management
+#[cfg(feature = "native")]
 pub mod layers;
 
 // Tools and utilities
+#[cfg(feature = "native")]
 pub mod tools;
 
 // Dimensions and annotations
+#[cfg(feature = "native")]
 pub mod dimensions;
 
 // Constraint solver
+#[cfg(feature = "native")]
 pub mod constraints;
 
 // Plugin system
+#[cfg(feature = "native")]
 pub mod plugins;
 
 // Enterprise features
+#[cfg(feature = "native")]
 pub mod enterprise;
 
 // Viewport rendering system
+#[cfg(feature = "native")]
 pub mod viewport;
 
 // Compression system
+#[cfg(feature = "native")]
 pub mod compression;
 
 // Analytics and telemetry
+#[cfg(feature = "native")]
 pub mod analytics;
 
 // Database layer
+#[cfg(feature = "native")]
 pub mod database;
 
 // 3D modeling engine
+#[cfg(feature = "native")]
 pub mod engine3d;
 
+// Standard parts library
+#[cfg(feature = "native")]
+pub mod parts;
+
+// Assemblies of placed part files
+#[cfg(feature = "native")]
+pub mod assembly;
+
 // Scheduling and monitoring system
+#[cfg(feature = "native")]
 pub mod scheduling;
 
 // Accessibility scanning and remediation
+#[cfg(feature = "native")]
 pub mod accessibility;
 
 // SaaS infrastructure
+#[cfg(feature = "native")]
 pub mod saas;
 
 // REST API gateway
+#[cfg(feature = "native")]
 pub mod api;
 
 // Authentication and authorization
+#[cfg(feature = "native")]
 pub mod auth;
 
 // Team collaboration system
+#[cfg(feature = "native")]
 pub mod teams;
 
 // CI/CD integrations
+#[cfg(feature = "native")]
 pub mod integrations;
 
 // AI/ML engine
+#[cfg(feature = "native")]
 pub mod ai;
 
+// WebAssembly bindings
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
 // Re-export commonly used types
 pub use core::{
     color::Color,
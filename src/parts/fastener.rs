@@ -0,0 +1,621 @@
+//! Parametric hex bolts, hex nuts and plain washers
+//!
+//! A [`Fastener`] is looked up from the size tables by standard, kind and
+//! size. It draws itself as a top (plan) or side (elevation) view following
+//! the usual thread conventions, builds a closed solid for the 3D engine, and
+//! inserts itself into a drawing as a block whose attributes carry the bill
+//! of materials data.
+//!
+//! Fastener coordinates put the bearing face of the head (or the underside of
+//! a nut or washer) on the origin, with the axis along +Z in 3D and +Y in the
+//! side view. Bolt shanks run down the negative axis.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use uuid::Uuid;
+
+use super::tables::{FastenerSize, INCH, INCH_LENGTHS, METRIC, METRIC_LENGTHS};
+use super::{PartsError, PartsResult, BOM_CATEGORY, FASTENER_CATEGORY};
+use crate::core::{Point3, Vector3};
+use crate::engine3d::boolean::{boolean_operation, BooleanOp};
+use crate::engine3d::mesh::HalfEdgeMesh;
+use crate::engine3d::topology::{ExtrudeOperation, TopologyError};
+use crate::io::document::{Arc, Block, Circle, Document, Entity, GeometryType, Insert, Line, Polyline, Vec3, Vertex};
+use crate::io::units::Unit;
+
+/// Layer block contents are drawn on, so inserts take the insert's layer
+const BLOCK_LAYER: &str = "0";
+
+/// Family of standards a fastener is drawn to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FastenerStandard {
+    /// ISO metric (millimeters)
+    Iso,
+    /// ANSI/ASME unified inch (inches)
+    Ansi,
+}
+
+impl FastenerStandard {
+    /// Units the size table is in
+    pub fn unit(&self) -> Unit {
+        match self {
+            FastenerStandard::Iso => Unit::Millimeters,
+            FastenerStandard::Ansi => Unit::Inches,
+        }
+    }
+
+    /// Size table rows
+    pub fn sizes(&self) -> &'static [FastenerSize] {
+        match self {
+            FastenerStandard::Iso => METRIC,
+            FastenerStandard::Ansi => INCH,
+        }
+    }
+
+    /// Preferred bolt lengths
+    pub fn lengths(&self) -> &'static [f64] {
+        match self {
+            FastenerStandard::Iso => METRIC_LENGTHS,
+            FastenerStandard::Ansi => INCH_LENGTHS,
+        }
+    }
+
+    /// Look up a size, ignoring case (`m8`, `3/8-16`)
+    pub fn size(&self, size: &str) -> PartsResult<&'static FastenerSize> {
+        self.sizes()
+            .iter()
+            .find(|row| row.size.eq_ignore_ascii_case(size.trim()))
+            .ok_or_else(|| PartsError::UnknownSize {
+                standard: *self,
+                size: size.to_string(),
+            })
+    }
+}
+
+/// Kind of fastener
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FastenerKind {
+    /// Hex head bolt or cap screw
+    HexBolt,
+    /// Hex nut
+    HexNut,
+    /// Plain washer
+    Washer,
+}
+
+impl FastenerKind {
+    /// Standard number for a standard family
+    pub fn standard_number(&self, standard: FastenerStandard) -> &'static str {
+        match (standard, self) {
+            (FastenerStandard::Iso, FastenerKind::HexBolt) => "ISO 4017",
+            (FastenerStandard::Iso, FastenerKind::HexNut) => "ISO 4032",
+            (FastenerStandard::Iso, FastenerKind::Washer) => "ISO 7089",
+            (FastenerStandard::Ansi, FastenerKind::HexBolt) => "ASME B18.2.1",
+            (FastenerStandard::Ansi, FastenerKind::HexNut) => "ASME B18.2.2",
+            (FastenerStandard::Ansi, FastenerKind::Washer) => "ASME B18.22.1",
+        }
+    }
+
+    /// Description used in parts lists
+    pub fn description(&self, standard: FastenerStandard) -> &'static str {
+        match (standard, self) {
+            (FastenerStandard::Iso, FastenerKind::HexBolt) => "Hex head screw",
+            (FastenerStandard::Ansi, FastenerKind::HexBolt) => "Hex cap screw",
+            (_, FastenerKind::HexNut) => "Hex nut",
+            (_, FastenerKind::Washer) => "Plain washer",
+        }
+    }
+}
+
+/// View a fastener block is drawn in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PartView {
+    /// Looking down the axis
+    Top,
+    /// Looking across the axis, axis along +Y
+    Side,
+}
+
+impl PartView {
+    fn suffix(&self) -> &'static str {
+        match self {
+            PartView::Top => "TOP",
+            PartView::Side => "SIDE",
+        }
+    }
+}
+
+/// A standard fastener resolved against the size tables
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fastener {
+    /// Standard family
+    pub standard: FastenerStandard,
+    /// Kind of fastener
+    pub kind: FastenerKind,
+    /// Size table row
+    pub size: &'static FastenerSize,
+    /// Bolt length under the head; `None` for nuts and washers
+    pub length: Option<f64>,
+    /// Material or property class for the parts list
+    pub material: String,
+}
+
+impl Fastener {
+    /// Hex bolt of a stocked length
+    pub fn hex_bolt(standard: FastenerStandard, size: &str, length: f64) -> PartsResult<Self> {
+        let row = standard.size(size)?;
+        let (min, max) = row.lengths;
+        let stocked = standard.lengths().iter().any(|l| (l - length).abs() < 1e-9);
+        if !stocked || length < min - 1e-9 || length > max + 1e-9 {
+            return Err(PartsError::UnavailableLength {
+                size: row.size.to_string(),
+                length,
+            });
+        }
+        Ok(Self::new(standard, FastenerKind::HexBolt, row, Some(length)))
+    }
+
+    /// Hex nut
+    pub fn hex_nut(standard: FastenerStandard, size: &str) -> PartsResult<Self> {
+        Ok(Self::new(standard, FastenerKind::HexNut, standard.size(size)?, None))
+    }
+
+    /// Plain washer for a bolt size
+    pub fn washer(standard: FastenerStandard, size: &str) -> PartsResult<Self> {
+        Ok(Self::new(standard, FastenerKind::Washer, standard.size(size)?, None))
+    }
+
+    fn new(standard: FastenerStandard, kind: FastenerKind, size: &'static FastenerSize, length: Option<f64>) -> Self {
+        Self {
+            standard,
+            kind,
+            size,
+            length,
+            material: "Steel".to_string(),
+        }
+    }
+
+    /// Set the material or property class
+    pub fn with_material(mut self, material: impl Into<String>) -> Self {
+        self.material = material.into();
+        self
+    }
+
+    /// Standard designation (`ISO 4017 M8x30`, `ASME B18.2.1 3/8-16 x 1-1/4`)
+    pub fn designation(&self) -> String {
+        format!("{} {}", self.kind.standard_number(self.standard), self.size_label())
+    }
+
+    /// Size with length for bolts, nominal size for washers
+    pub fn size_label(&self) -> String {
+        match (self.kind, self.standard, self.length) {
+            (FastenerKind::HexBolt, FastenerStandard::Iso, Some(length)) => {
+                format!("{}x{}", self.size.size, format_length(self.standard, length))
+            }
+            (FastenerKind::HexBolt, FastenerStandard::Ansi, Some(length)) => {
+                format!("{} x {}", self.size.size, format_length(self.standard, length))
+            }
+            (FastenerKind::Washer, FastenerStandard::Iso, _) => self.size.size.trim_start_matches('M').to_string(),
+            (FastenerKind::Washer, FastenerStandard::Ansi, _) => self.size.nominal().to_string(),
+            _ => self.size.size.to_string(),
+        }
+    }
+
+    /// Parts list description (`Hex nut M8`)
+    pub fn description(&self) -> String {
+        format!("{} {}", self.kind.description(self.standard), self.size_label())
+    }
+
+    /// Block name for a view, safe for DXF (`ISO4017_M8X30_SIDE`)
+    pub fn block_name(&self, view: PartView) -> String {
+        let clean = |text: &str| -> String {
+            text.chars()
+                .filter(|c| !c.is_whitespace())
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                        c.to_ascii_uppercase()
+                    } else {
+                        '_'
+                    }
+                })
+                .collect()
+        };
+        format!(
+            "{}_{}_{}",
+            clean(self.kind.standard_number(self.standard)),
+            clean(&self.size_label()),
+            view.suffix()
+        )
+    }
+
+    /// Bill of materials attributes carried by inserted blocks
+    pub fn bom_attributes(&self) -> HashMap<String, String> {
+        let mut attributes = HashMap::from([
+            (BOM_CATEGORY.to_string(), FASTENER_CATEGORY.to_string()),
+            ("DESCRIPTION".to_string(), self.description()),
+            (
+                "STANDARD".to_string(),
+                self.kind.standard_number(self.standard).to_string(),
+            ),
+            ("DESIGNATION".to_string(), self.designation()),
+            ("SIZE".to_string(), self.size.size.to_string()),
+            ("MATERIAL".to_string(), self.material.clone()),
+            ("QTY".to_string(), "1".to_string()),
+        ]);
+        if let Some(length) = self.length {
+            attributes.insert("LENGTH".to_string(), format_length(self.standard, length));
+        }
+        attributes
+    }
+
+    /// Overall extent along the axis, from the bolt tip to the top
+    pub fn height(&self) -> f64 {
+        match self.kind {
+            FastenerKind::HexBolt => self.size.head_height + self.length.unwrap_or(0.0),
+            FastenerKind::HexNut => self.size.nut_height,
+            FastenerKind::Washer => self.size.washer_thickness,
+        }
+    }
+
+    /// Length of thread drawn on a bolt
+    ///
+    /// ISO 4017 screws are threaded to the head; inch cap screws carry
+    /// `2d + 1/4"` of thread.
+    pub fn thread_length(&self) -> f64 {
+        let length = self.length.unwrap_or(0.0);
+        match self.standard {
+            FastenerStandard::Iso => length,
+            FastenerStandard::Ansi => length.min(2.0 * self.size.diameter + 0.25),
+        }
+    }
+
+    /// 2D entities for a view, in the size table's units
+    pub fn view_entities(&self, view: PartView) -> Vec<Entity> {
+        let s = self.size;
+        let mut geometry = Vec::new();
+        match (self.kind, view) {
+            (FastenerKind::HexBolt, PartView::Top) => {
+                geometry.push(hexagon(s.head_across_flats));
+                geometry.push(circle(s.head_across_flats / 2.0));
+            }
+            (FastenerKind::HexNut, PartView::Top) => {
+                geometry.push(hexagon(s.nut_across_flats));
+                geometry.push(circle(s.nut_across_flats / 2.0));
+                // Internal thread: minor diameter solid, major as a 3/4 arc
+                geometry.push(circle(s.minor_diameter() / 2.0));
+                geometry.push(GeometryType::Arc(Arc {
+                    center: Vec3::zero(),
+                    radius: s.diameter / 2.0,
+                    start_angle: 0.0,
+                    end_angle: 1.5 * PI,
+                    normal: Vec3::unit_z(),
+                }));
+            }
+            (FastenerKind::Washer, PartView::Top) => {
+                geometry.push(circle(s.washer_outer / 2.0));
+                geometry.push(circle(s.washer_inner / 2.0));
+            }
+            (FastenerKind::HexBolt, PartView::Side) => {
+                let length = self.length.unwrap_or(0.0);
+                geometry.extend(hex_elevation(s.head_across_flats, 0.0, s.head_height));
+                geometry.push(rectangle(-s.diameter / 2.0, -length, s.diameter / 2.0, 0.0));
+                // External thread: minor diameter lines over the thread length
+                let thread_end = -length + self.thread_length();
+                let minor = s.minor_diameter() / 2.0;
+                for x in [-minor, minor] {
+                    geometry.push(line(x, -length, x, thread_end));
+                }
+                if thread_end < -1e-9 {
+                    geometry.push(line(-s.diameter / 2.0, thread_end, s.diameter / 2.0, thread_end));
+                }
+            }
+            (FastenerKind::HexNut, PartView::Side) => {
+                geometry.extend(hex_elevation(s.nut_across_flats, 0.0, s.nut_height));
+            }
+            (FastenerKind::Washer, PartView::Side) => {
+                let r = s.washer_outer / 2.0;
+                geometry.push(rectangle(-r, 0.0, r, s.washer_thickness));
+            }
+        }
+        geometry
+            .into_iter()
+            .map(|g| Entity::new(g, BLOCK_LAYER.to_string()))
+            .collect()
+    }
+
+    /// Block definition for a view, scaled to drawing units
+    pub fn block(&self, view: PartView, units: Unit) -> Block {
+        let scale = self.standard.unit().convert_to(1.0, units);
+        let mut entities = self.view_entities(view);
+        if (scale - 1.0).abs() > 1e-12 {
+            entities.iter_mut().for_each(|e| scale_geometry(&mut e.geometry, scale));
+        }
+        Block {
+            name: self.block_name(view),
+            base_point: Vec3::zero(),
+            entities,
+            description: self.description(),
+        }
+    }
+
+    /// Closed solid in the size table's units, round features faceted with
+    /// `segments` sides
+    pub fn solid(&self, segments: usize) -> PartsResult<HalfEdgeMesh> {
+        if segments < 6 {
+            return Err(TopologyError::InvalidSegmentCount.into());
+        }
+        let s = self.size;
+        let mesh = match self.kind {
+            FastenerKind::HexBolt => {
+                let length = self.length.unwrap_or(0.0);
+                let head = prism(&hexagon_points(s.head_across_flats), 0.0, s.head_height)?;
+                let shank = prism(&polygon_points(s.diameter / 2.0, segments), -length, 0.0)?;
+                boolean_operation(&head, &shank, BooleanOp::Union)?
+            }
+            FastenerKind::HexNut => {
+                let body = prism(&hexagon_points(s.nut_across_flats), 0.0, s.nut_height)?;
+                let hole = prism(
+                    &polygon_points(s.diameter / 2.0, segments),
+                    -s.nut_height,
+                    2.0 * s.nut_height,
+                )?;
+                boolean_operation(&body, &hole, BooleanOp::Difference)?
+            }
+            FastenerKind::Washer => {
+                let t = s.washer_thickness;
+                let disc = prism(&polygon_points(s.washer_outer / 2.0, segments), 0.0, t)?;
+                let hole = prism(&polygon_points(s.washer_inner / 2.0, segments), -t, 2.0 * t)?;
+                boolean_operation(&disc, &hole, BooleanOp::Difference)?
+            }
+        };
+        Ok(mesh)
+    }
+}
+
+/// Insert a fastener into a drawing as a block carrying its BOM attributes
+///
+/// The block is defined on first use, scaled to the drawing's units. Returns
+/// the id of the new insert.
+pub fn insert_fastener(
+    doc: &mut Document,
+    fastener: &Fastener,
+    view: PartView,
+    position: Vec3,
+    rotation: f64,
+    layer: &str,
+) -> Uuid {
+    let name = fastener.block_name(view);
+    if doc.get_block(&name).is_none() {
+        doc.add_block(fastener.block(view, doc.settings.units));
+    }
+    doc.add_entity(Entity::new(
+        GeometryType::Insert(Insert {
+            block_name: name,
+            position,
+            scale: Vec3::new(1.0, 1.0, 1.0),
+            rotation,
+            attributes: fastener.bom_attributes(),
+        }),
+        layer.to_string(),
+    ))
+}
+
+/// Bolt length as written in designations: millimeters, or fractional
+/// inches to the sixteenth (`1-1/4`)
+fn format_length(standard: FastenerStandard, length: f64) -> String {
+    match standard {
+        FastenerStandard::Iso => format!("{}", length),
+        FastenerStandard::Ansi => {
+            let sixteenths = (length * 16.0).round() as u32;
+            let (whole, mut num, mut den) = (sixteenths / 16, sixteenths % 16, 16);
+            while num > 0 && num % 2 == 0 {
+                num /= 2;
+                den /= 2;
+            }
+            match (whole, num) {
+                (w, 0) => w.to_string(),
+                (0, n) => format!("{}/{}", n, den),
+                (w, n) => format!("{}-{}/{}", w, n, den),
+            }
+        }
+    }
+}
+
+/// Hexagon corners with flats top and bottom
+fn hexagon_points(across_flats: f64) -> Vec<(f64, f64)> {
+    let r = across_flats / 3f64.sqrt();
+    (0..6)
+        .map(|i| {
+            let a = i as f64 * PI / 3.0;
+            (r * a.cos(), r * a.sin())
+        })
+        .collect()
+}
+
+fn polygon_points(radius: f64, segments: usize) -> Vec<(f64, f64)> {
+    (0..segments)
+        .map(|i| {
+            let a = 2.0 * PI * i as f64 / segments as f64;
+            (radius * a.cos(), radius * a.sin())
+        })
+        .collect()
+}
+
+/// Counter-clockwise outline extruded from `z0` to `z1`
+fn prism(outline: &[(f64, f64)], z0: f64, z1: f64) -> Result<HalfEdgeMesh, TopologyError> {
+    let profile: Vec<Point3> = outline.iter().map(|&(x, y)| Point3::new(x, y, z0)).collect();
+    ExtrudeOperation {
+        direction: Vector3::new(0.0, 0.0, z1 - z0),
+        ..Default::default()
+    }
+    .extrude_profile(&profile)
+}
+
+fn hexagon(across_flats: f64) -> GeometryType {
+    GeometryType::Polyline(Polyline {
+        vertices: hexagon_points(across_flats)
+            .into_iter()
+            .map(|(x, y)| Vertex {
+                position: Vec3::new(x, y, 0.0),
+                bulge: 0.0,
+            })
+            .collect(),
+        closed: true,
+    })
+}
+
+/// Hex body seen across corners, with the two visible corner edges
+fn hex_elevation(across_flats: f64, y0: f64, y1: f64) -> Vec<GeometryType> {
+    let half = across_flats / 3f64.sqrt();
+    vec![
+        rectangle(-half, y0, half, y1),
+        line(-half / 2.0, y0, -half / 2.0, y1),
+        line(half / 2.0, y0, half / 2.0, y1),
+    ]
+}
+
+fn circle(radius: f64) -> GeometryType {
+    GeometryType::Circle(Circle {
+        center: Vec3::zero(),
+        radius,
+        normal: Vec3::unit_z(),
+    })
+}
+
+fn line(x0: f64, y0: f64, x1: f64, y1: f64) -> GeometryType {
+    GeometryType::Line(Line {
+        start: Vec3::new(x0, y0, 0.0),
+        end: Vec3::new(x1, y1, 0.0),
+    })
+}
+
+fn rectangle(x0: f64, y0: f64, x1: f64, y1: f64) -> GeometryType {
+    GeometryType::Polyline(Polyline {
+        vertices: [(x0, y0), (x1, y0), (x1, y1), (x0, y1)]
+            .into_iter()
+            .map(|(x, y)| Vertex {
+                position: Vec3::new(x, y, 0.0),
+                bulge: 0.0,
+            })
+            .collect(),
+        closed: true,
+    })
+}
+
+/// Scale the geometry kinds fastener views are made of about the origin
+fn scale_geometry(geometry: &mut GeometryType, scale: f64) {
+    let s = |p: &mut Vec3| *p = Vec3::new(p.x * scale, p.y * scale, p.z * scale);
+    match geometry {
+        GeometryType::Line(l) => {
+            s(&mut l.start);
+            s(&mut l.end);
+        }
+        GeometryType::Circle(c) => {
+            s(&mut c.center);
+            c.radius *= scale;
+        }
+        GeometryType::Arc(a) => {
+            s(&mut a.center);
+            a.radius *= scale;
+        }
+        GeometryType::Polyline(p) => p.vertices.iter_mut().for_each(|v| s(&mut v.position)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine3d::boolean::MeshValidity;
+
+    #[test]
+    fn test_lookup_and_designation() {
+        let bolt = Fastener::hex_bolt(FastenerStandard::Iso, "m8", 30.0).unwrap();
+        assert_eq!(bolt.designation(), "ISO 4017 M8x30");
+        assert_eq!(bolt.block_name(PartView::Side), "ISO4017_M8X30_SIDE");
+        assert_eq!(bolt.height(), 35.3);
+
+        let screw = Fastener::hex_bolt(FastenerStandard::Ansi, "3/8-16", 1.25).unwrap();
+        assert_eq!(screw.designation(), "ASME B18.2.1 3/8-16 x 1-1/4");
+        assert_eq!(screw.bom_attributes()["LENGTH"], "1-1/4");
+        assert!((screw.thread_length() - 1.0).abs() < 1e-12);
+
+        let washer = Fastener::washer(FastenerStandard::Iso, "M10").unwrap();
+        assert_eq!(washer.designation(), "ISO 7089 10");
+        let washer = Fastener::washer(FastenerStandard::Ansi, "1/2-13").unwrap();
+        assert_eq!(washer.description(), "Plain washer 1/2");
+
+        assert!(matches!(
+            Fastener::hex_nut(FastenerStandard::Iso, "M7"),
+            Err(PartsError::UnknownSize { .. })
+        ));
+        assert!(matches!(
+            Fastener::hex_bolt(FastenerStandard::Iso, "M8", 33.0),
+            Err(PartsError::UnavailableLength { .. })
+        ));
+        assert!(matches!(
+            Fastener::hex_bolt(FastenerStandard::Iso, "M3", 80.0),
+            Err(PartsError::UnavailableLength { .. })
+        ));
+    }
+
+    #[test]
+    fn test_solids_are_closed() {
+        let bolt = Fastener::hex_bolt(FastenerStandard::Iso, "M8", 30.0).unwrap();
+        let nut = Fastener::hex_nut(FastenerStandard::Iso, "M8").unwrap();
+        let washer = Fastener::washer(FastenerStandard::Iso, "M8").unwrap();
+        let segments = 24;
+        let polygon_area = |r: f64| 0.5 * segments as f64 * r * r * (2.0 * PI / segments as f64).sin();
+        let hex_area = |s: f64| 3f64.sqrt() / 2.0 * s * s;
+
+        let expected = [
+            hex_area(13.0) * 5.3 + polygon_area(4.0) * 30.0,
+            (hex_area(13.0) - polygon_area(4.0)) * 6.8,
+            (polygon_area(8.0) - polygon_area(4.2)) * 1.6,
+        ];
+        for (part, volume) in [bolt, nut, washer].iter().zip(expected) {
+            let validity = MeshValidity::check(&part.solid(segments).unwrap()).unwrap();
+            assert!(validity.is_valid(), "{}: {:?}", part.designation(), validity);
+            assert!(
+                (validity.volume - volume).abs() < 1e-6 * volume,
+                "{}",
+                part.designation()
+            );
+        }
+    }
+
+    #[test]
+    fn test_insert_carries_bom() {
+        let mut doc = Document::new();
+        doc.settings.units = Unit::Millimeters;
+        let screw = Fastener::hex_bolt(FastenerStandard::Ansi, "1/2-13", 2.0)
+            .unwrap()
+            .with_material("Grade 5");
+
+        let first = insert_fastener(&mut doc, &screw, PartView::Top, Vec3::zero(), 0.0, "FASTENERS");
+        insert_fastener(
+            &mut doc,
+            &screw,
+            PartView::Top,
+            Vec3::new(50.0, 0.0, 0.0),
+            0.0,
+            "FASTENERS",
+        );
+        assert_eq!(doc.blocks.len(), 1);
+
+        let block = doc.get_block(&screw.block_name(PartView::Top)).unwrap();
+        let GeometryType::Circle(chamfer) = &block.entities[1].geometry else {
+            panic!("expected the head chamfer circle");
+        };
+        // 3/4" across flats, drawn in millimeters
+        assert!((chamfer.radius - 9.525).abs() < 1e-9);
+
+        let GeometryType::Insert(insert) = &doc.get_entity(first).unwrap().geometry else {
+            panic!("expected an insert");
+        };
+        assert_eq!(insert.attributes["DESIGNATION"], "ASME B18.2.1 1/2-13 x 2");
+        assert_eq!(insert.attributes["MATERIAL"], "Grade 5");
+        assert_eq!(insert.attributes[BOM_CATEGORY], FASTENER_CATEGORY);
+    }
+}
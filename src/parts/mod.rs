@@ -0,0 +1,93 @@
+//! # CADDY Standard Parts
+//!
+//! Parametric standard parts generated from size tables:
+//!
+//! - **Fasteners**: ISO metric and ANSI/ASME inch hex bolts, hex nuts and
+//!   plain washers, drawn as top or side view blocks and built as closed
+//!   solids for the 3D engine
+//! - **Intelligent blocks**: inserted parts carry their description,
+//!   standard, size, length and material as insert attributes
+//! - **Bills of materials**: [`bom_schedule`] counts inserted parts by
+//!   designation and material with the takeoff schedules
+//!
+//! ## Example
+//!
+//! ```
+//! use caddy::io::document::{Document, Vec3};
+//! use caddy::parts::{bom_schedule, insert_fastener, Fastener, FastenerStandard, PartView};
+//!
+//! let mut doc = Document::new();
+//! let bolt = Fastener::hex_bolt(FastenerStandard::Iso, "M10", 40.0).unwrap();
+//! let nut = Fastener::hex_nut(FastenerStandard::Iso, "M10").unwrap();
+//! for x in [0.0, 60.0] {
+//!     insert_fastener(&mut doc, &bolt, PartView::Side, Vec3::new(x, 0.0, 0.0), 0.0, "0");
+//!     insert_fastener(&mut doc, &nut, PartView::Side, Vec3::new(x, -30.0, 0.0), 0.0, "0");
+//! }
+//!
+//! let bom = bom_schedule().evaluate(&doc).unwrap();
+//! assert_eq!(bom.value(&["ISO 4017 M10x40", "Steel"], "qty"), Some(2.0));
+//! ```
+
+pub mod fastener;
+pub mod tables;
+
+use thiserror::Error;
+
+use crate::engine3d::boolean::BooleanError;
+use crate::engine3d::topology::TopologyError;
+use crate::takeoff::{Column, EntityFilter, GroupBy, ScheduleDefinition};
+
+pub use fastener::{insert_fastener, Fastener, FastenerKind, FastenerStandard, PartView};
+pub use tables::FastenerSize;
+
+/// Insert attribute naming the kind of standard part
+pub const BOM_CATEGORY: &str = "CATEGORY";
+
+/// [`BOM_CATEGORY`] value of fasteners
+pub const FASTENER_CATEGORY: &str = "FASTENER";
+
+/// Parts list of inserted fasteners: quantity per designation and material
+pub fn bom_schedule() -> ScheduleDefinition {
+    ScheduleDefinition::new("Fasteners")
+        .filter(EntityFilter {
+            entity_types: vec!["Insert".to_string()],
+            attributes: vec![(BOM_CATEGORY.to_string(), FASTENER_CATEGORY.to_string())],
+            ..Default::default()
+        })
+        .group_by(GroupBy::Attribute("DESIGNATION".to_string()))
+        .group_by(GroupBy::Attribute("MATERIAL".to_string()))
+        .column(Column::count("qty").with_header("Qty"))
+}
+
+/// Standard parts errors
+#[derive(Debug, Error)]
+pub enum PartsError {
+    /// Size is not in the standard's table
+    #[error("No {standard:?} size {size}")]
+    UnknownSize {
+        /// Standard family searched
+        standard: FastenerStandard,
+        /// Requested size
+        size: String,
+    },
+
+    /// Bolt length is not stocked for the size
+    #[error("Length {length} is not a stocked length for {size}")]
+    UnavailableLength {
+        /// Size designation
+        size: String,
+        /// Requested length
+        length: f64,
+    },
+
+    /// Building a solid failed
+    #[error(transparent)]
+    Solid(#[from] TopologyError),
+
+    /// Combining solids failed
+    #[error(transparent)]
+    Boolean(#[from] BooleanError),
+}
+
+/// Result type for standard parts operations
+pub type PartsResult<T> = Result<T, PartsError>;
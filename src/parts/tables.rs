@@ -0,0 +1,209 @@
+//! Fastener size tables
+//!
+//! Nominal dimensions for hex bolts, hex nuts and plain washers. Metric
+//! rows follow ISO 4017 (hex head screws), ISO 4032 (hex nuts, style 1) and
+//! ISO 7089 (plain washers, normal series), in millimeters. Inch rows follow
+//! ASME B18.2.1 (hex cap screws), ASME B18.2.2 (hex nuts) and ASME B18.22.1
+//! (type A plain washers, narrow), in inches.
+//!
+//! Values are the nominal (maximum) dimensions used for drawing; they are not
+//! a substitute for the standard when checking tolerances.
+
+/// One fastener size across the bolt, nut and washer tables
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FastenerSize {
+    /// Size designation (`M8`, `3/8-16`)
+    pub size: &'static str,
+    /// Nominal thread diameter
+    pub diameter: f64,
+    /// Thread pitch (the reciprocal of threads per inch for inch sizes)
+    pub pitch: f64,
+    /// Bolt head width across flats
+    pub head_across_flats: f64,
+    /// Bolt head height
+    pub head_height: f64,
+    /// Nut width across flats
+    pub nut_across_flats: f64,
+    /// Nut height
+    pub nut_height: f64,
+    /// Washer hole diameter
+    pub washer_inner: f64,
+    /// Washer outside diameter
+    pub washer_outer: f64,
+    /// Washer thickness
+    pub washer_thickness: f64,
+    /// Shortest and longest stocked bolt lengths
+    pub lengths: (f64, f64),
+}
+
+impl FastenerSize {
+    /// Approximate thread minor diameter, as drawn for thread conventions
+    pub fn minor_diameter(&self) -> f64 {
+        self.diameter - 1.226_869 * self.pitch
+    }
+
+    /// Nominal size without the thread count (`3/8` for `3/8-16`)
+    pub fn nominal(&self) -> &'static str {
+        match self.size.find('-') {
+            Some(i) if !self.size.starts_with('M') => &self.size[..i],
+            _ => self.size,
+        }
+    }
+}
+
+/// Preferred metric bolt lengths (ISO 4017), in millimeters
+pub const METRIC_LENGTHS: &[f64] = &[
+    6.0, 8.0, 10.0, 12.0, 16.0, 20.0, 25.0, 30.0, 35.0, 40.0, 45.0, 50.0, 55.0, 60.0, 65.0, 70.0, 80.0, 90.0, 100.0,
+    110.0, 120.0, 130.0, 140.0, 150.0, 160.0, 180.0, 200.0,
+];
+
+/// Preferred inch bolt lengths (ASME B18.2.1), in inches
+pub const INCH_LENGTHS: &[f64] = &[
+    0.5, 0.625, 0.75, 0.875, 1.0, 1.25, 1.5, 1.75, 2.0, 2.25, 2.5, 2.75, 3.0, 3.5, 4.0, 4.5, 5.0, 5.5, 6.0,
+];
+
+const fn metric(
+    size: &'static str,
+    diameter: f64,
+    pitch: f64,
+    (across_flats, head_height): (f64, f64),
+    nut_height: f64,
+    washer: (f64, f64, f64),
+    lengths: (f64, f64),
+) -> FastenerSize {
+    FastenerSize {
+        size,
+        diameter,
+        pitch,
+        head_across_flats: across_flats,
+        head_height,
+        nut_across_flats: across_flats,
+        nut_height,
+        washer_inner: washer.0,
+        washer_outer: washer.1,
+        washer_thickness: washer.2,
+        lengths,
+    }
+}
+
+/// Metric coarse-thread sizes
+pub const METRIC: &[FastenerSize] = &[
+    metric("M3", 3.0, 0.5, (5.5, 2.0), 2.4, (3.2, 7.0, 0.5), (6.0, 30.0)),
+    metric("M4", 4.0, 0.7, (7.0, 2.8), 3.2, (4.3, 9.0, 0.8), (8.0, 40.0)),
+    metric("M5", 5.0, 0.8, (8.0, 3.5), 4.7, (5.3, 10.0, 1.0), (10.0, 50.0)),
+    metric("M6", 6.0, 1.0, (10.0, 4.0), 5.2, (6.4, 12.0, 1.6), (12.0, 60.0)),
+    metric("M8", 8.0, 1.25, (13.0, 5.3), 6.8, (8.4, 16.0, 1.6), (16.0, 80.0)),
+    metric("M10", 10.0, 1.5, (16.0, 6.4), 8.4, (10.5, 20.0, 2.0), (20.0, 100.0)),
+    metric("M12", 12.0, 1.75, (18.0, 7.5), 10.8, (13.0, 24.0, 2.5), (25.0, 120.0)),
+    metric("M16", 16.0, 2.0, (24.0, 10.0), 14.8, (17.0, 30.0, 3.0), (30.0, 150.0)),
+    metric("M20", 20.0, 2.5, (30.0, 12.5), 18.0, (21.0, 37.0, 3.0), (40.0, 200.0)),
+    metric("M24", 24.0, 3.0, (36.0, 15.0), 21.5, (25.0, 44.0, 4.0), (50.0, 200.0)),
+];
+
+/// Unified coarse (UNC) inch sizes
+pub const INCH: &[FastenerSize] = &[
+    FastenerSize {
+        size: "1/4-20",
+        diameter: 0.25,
+        pitch: 1.0 / 20.0,
+        head_across_flats: 0.4375,
+        head_height: 0.156,
+        nut_across_flats: 0.4375,
+        nut_height: 0.219,
+        washer_inner: 0.281,
+        washer_outer: 0.625,
+        washer_thickness: 0.065,
+        lengths: (0.5, 4.0),
+    },
+    FastenerSize {
+        size: "5/16-18",
+        diameter: 0.3125,
+        pitch: 1.0 / 18.0,
+        head_across_flats: 0.5,
+        head_height: 0.203,
+        nut_across_flats: 0.5,
+        nut_height: 0.266,
+        washer_inner: 0.344,
+        washer_outer: 0.688,
+        washer_thickness: 0.065,
+        lengths: (0.5, 4.0),
+    },
+    FastenerSize {
+        size: "3/8-16",
+        diameter: 0.375,
+        pitch: 1.0 / 16.0,
+        head_across_flats: 0.5625,
+        head_height: 0.234,
+        nut_across_flats: 0.5625,
+        nut_height: 0.328,
+        washer_inner: 0.406,
+        washer_outer: 0.812,
+        washer_thickness: 0.065,
+        lengths: (0.625, 5.0),
+    },
+    FastenerSize {
+        size: "7/16-14",
+        diameter: 0.4375,
+        pitch: 1.0 / 14.0,
+        head_across_flats: 0.625,
+        head_height: 0.281,
+        nut_across_flats: 0.6875,
+        nut_height: 0.375,
+        washer_inner: 0.469,
+        washer_outer: 0.922,
+        washer_thickness: 0.065,
+        lengths: (0.75, 5.0),
+    },
+    FastenerSize {
+        size: "1/2-13",
+        diameter: 0.5,
+        pitch: 1.0 / 13.0,
+        head_across_flats: 0.75,
+        head_height: 0.312,
+        nut_across_flats: 0.75,
+        nut_height: 0.438,
+        washer_inner: 0.531,
+        washer_outer: 1.062,
+        washer_thickness: 0.095,
+        lengths: (0.75, 6.0),
+    },
+    FastenerSize {
+        size: "5/8-11",
+        diameter: 0.625,
+        pitch: 1.0 / 11.0,
+        head_across_flats: 0.9375,
+        head_height: 0.391,
+        nut_across_flats: 0.9375,
+        nut_height: 0.547,
+        washer_inner: 0.656,
+        washer_outer: 1.312,
+        washer_thickness: 0.095,
+        lengths: (1.0, 6.0),
+    },
+    FastenerSize {
+        size: "3/4-10",
+        diameter: 0.75,
+        pitch: 1.0 / 10.0,
+        head_across_flats: 1.125,
+        head_height: 0.469,
+        nut_across_flats: 1.125,
+        nut_height: 0.641,
+        washer_inner: 0.812,
+        washer_outer: 1.469,
+        washer_thickness: 0.134,
+        lengths: (1.25, 6.0),
+    },
+    FastenerSize {
+        size: "1-8",
+        diameter: 1.0,
+        pitch: 1.0 / 8.0,
+        head_across_flats: 1.5,
+        head_height: 0.609,
+        nut_across_flats: 1.5,
+        nut_height: 0.859,
+        washer_inner: 1.062,
+        washer_outer: 2.0,
+        washer_thickness: 0.134,
+        lengths: (1.5, 6.0),
+    },
+];
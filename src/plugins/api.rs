@@ -9,6 +9,8 @@ use std::sync::Arc;
 use thiserror::Error;
 
 use super::permissions::{Permission, PermissionSet};
+use crate::io::document::Document;
+use crate::io::groups::{self, GroupError};
 
 /// Errors that can occur in plugin API operations
 #[derive(Debug, Error)]
@@ -200,6 +202,15 @@ impl PluginApi {
         })
     }
 
+    /// Get the API for named entity sets: groups, saved selections and
+    /// saved queries
+    pub fn sets(&self) -> ApiResult<SetsApi> {
+        self.require_permission(&Permission::GeometryRead)?;
+        Ok(SetsApi {
+            api: self.clone(),
+        })
+    }
+
     /// Get network API
     pub fn network(&self) -> ApiResult<NetworkApi> {
         self.require_permission(&Permission::NetworkAccess)?;
@@ -389,6 +400,55 @@ impl CommandApi {
     }
 }
 
+/// Named entity set API
+///
+/// Lets plugins work on semantic sets such as "grid lines" or "phase 1"
+/// by name, whether the document keeps them as groups, saved selections
+/// or saved queries. Changes need [`Permission::GeometryWrite`].
+#[derive(Clone)]
+pub struct SetsApi {
+    api: PluginApi,
+}
+
+impl SetsApi {
+    /// Names of the document's groups, saved selections and saved queries
+    pub fn names(&self, doc: &Document) -> Vec<String> {
+        groups::set_names(doc)
+    }
+
+    /// Entities of a named set
+    pub fn members(&self, doc: &Document, name: &str) -> ApiResult<Vec<uuid::Uuid>> {
+        groups::resolve(doc, name).map_err(set_error)
+    }
+
+    /// Save entities as a named selection, replacing one of the same name
+    pub fn save_selection(&self, doc: &mut Document, name: &str, entities: &[uuid::Uuid]) -> ApiResult<()> {
+        self.api.require_permission(&Permission::GeometryWrite)?;
+        groups::save_selection(doc, name, entities).map_err(set_error)
+    }
+
+    /// Group entities under a new name
+    pub fn create_group(&self, doc: &mut Document, name: &str, entities: &[uuid::Uuid]) -> ApiResult<()> {
+        self.api.require_permission(&Permission::GeometryWrite)?;
+        groups::create_group(doc, name, entities).map_err(set_error)
+    }
+
+    /// Add entities to an existing group
+    pub fn add_to_group(&self, doc: &mut Document, name: &str, entities: &[uuid::Uuid]) -> ApiResult<()> {
+        self.api.require_permission(&Permission::GeometryWrite)?;
+        groups::add_to_group(doc, name, entities).map_err(set_error)
+    }
+}
+
+fn set_error(error: GroupError) -> ApiError {
+    match error {
+        GroupError::UnknownGroup(_) | GroupError::UnknownSet(_) | GroupError::InvalidName(_) => {
+            ApiError::InvalidParameter(error.to_string())
+        }
+        _ => ApiError::CallFailed(error.to_string()),
+    }
+}
+
 /// Network API
 #[derive(Clone)]
 pub struct NetworkApi {
@@ -511,6 +571,27 @@ mod tests {
         assert!(!v1.is_compatible_with(&v4));
     }
 
+    #[test]
+    fn test_sets_api_permissions() {
+        let mut doc = Document::new();
+        let reader = PluginApi::new(PermissionSet::with_permissions(vec![Permission::GeometryRead]));
+        let sets = reader.sets().unwrap();
+        assert!(matches!(
+            sets.save_selection(&mut doc, "Phase 1", &[]),
+            Err(ApiError::PermissionDenied(_))
+        ));
+        assert!(matches!(sets.members(&doc, "Phase 1"), Err(ApiError::InvalidParameter(_))));
+
+        let writer = PluginApi::new(PermissionSet::with_permissions(vec![
+            Permission::GeometryRead,
+            Permission::GeometryWrite,
+        ]));
+        let id = uuid::Uuid::new_v4();
+        writer.sets().unwrap().create_group(&mut doc, "Grid lines", &[id]).unwrap();
+        assert_eq!(sets.members(&doc, "Grid lines").unwrap(), vec![id]);
+        assert_eq!(sets.names(&doc), vec!["Grid lines"]);
+    }
+
     #[test]
     fn test_api_version_parse() {
         let version = ApiVersion::parse("0.2.5").unwrap();
@@ -0,0 +1,341 @@
+//! GPU adapter selection and device loss detection
+//!
+//! [`RenderSettings`] names the adapter to render with: the best one of a
+//! kind, one matched by name or by its position in [`list_adapters`], or
+//! none at all. Whatever is asked for, selection falls back in order to
+//! any hardware adapter, to the platform's software adapter (WARP,
+//! llvmpipe or SwiftShader) and finally, for headless callers that allow
+//! it, to the CPU rasterizer in [`super::software`], so a server or VM
+//! without a GPU can still render.
+//!
+//! [`DeviceMonitor`] watches a device for loss, such as a driver reset or
+//! the GPU being removed; the renderer checks it before each frame and
+//! rebuilds its device and pipelines when it trips.
+
+use super::*;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Which adapter to render with
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AdapterPreference {
+    /// Best available: a discrete GPU over an integrated one
+    #[default]
+    Auto,
+    /// Integrated GPU over a discrete one, to save power
+    LowPower,
+    /// First adapter whose name contains this text, ignoring case
+    Named(String),
+    /// Adapter at this position in [`list_adapters`]
+    Index(usize),
+    /// Never touch the GPU; render on the CPU
+    Software,
+}
+
+/// Rendering settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderSettings {
+    /// Adapter to render with
+    pub adapter: AdapterPreference,
+    /// Graphics APIs to consider, as a comma list such as "vulkan,gl";
+    /// all of them when `None`
+    pub backends: Option<String>,
+    /// Fall back to the CPU rasterizer when no adapter is usable
+    pub software_fallback: bool,
+    /// Times to rebuild after losing the device before giving up on it
+    pub max_recoveries: u32,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            adapter: AdapterPreference::Auto,
+            backends: None,
+            software_fallback: true,
+            max_recoveries: 3,
+        }
+    }
+}
+
+impl RenderSettings {
+    /// Backends to enumerate
+    pub fn backends(&self) -> wgpu::Backends {
+        self.backends
+            .as_deref()
+            .map_or(wgpu::Backends::all(), wgpu::util::parse_backends_from_comma_list)
+    }
+
+    /// Instance over the configured backends
+    pub fn instance(&self) -> wgpu::Instance {
+        wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: self.backends(),
+            ..Default::default()
+        })
+    }
+}
+
+/// Description of an adapter, for listing in settings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdapterSummary {
+    /// Position in [`list_adapters`]
+    pub index: usize,
+    /// Adapter name as reported by the driver
+    pub name: String,
+    /// PCI vendor id
+    pub vendor: u32,
+    /// PCI device id
+    pub device: u32,
+    /// Discrete, integrated, virtual, CPU or other
+    pub device_type: wgpu::DeviceType,
+    /// Graphics API
+    pub backend: wgpu::Backend,
+}
+
+impl AdapterSummary {
+    /// Summary of an adapter's info
+    pub fn new(index: usize, info: &wgpu::AdapterInfo) -> Self {
+        Self {
+            index,
+            name: info.name.clone(),
+            vendor: info.vendor,
+            device: info.device,
+            device_type: info.device_type,
+            backend: info.backend,
+        }
+    }
+
+    /// Whether the adapter renders on the CPU
+    pub fn is_software(&self) -> bool {
+        self.device_type == wgpu::DeviceType::Cpu
+    }
+
+    /// Text for an adapter picker, e.g. "NVIDIA RTX A4000 (Vulkan, DiscreteGpu)"
+    pub fn label(&self) -> String {
+        format!("{} ({:?}, {:?})", self.name, self.backend, self.device_type)
+    }
+}
+
+/// Adapters available through an instance, in the order [`AdapterPreference::Index`] uses
+pub fn list_adapters(instance: &wgpu::Instance, settings: &RenderSettings) -> Vec<AdapterSummary> {
+    instance
+        .enumerate_adapters(settings.backends())
+        .iter()
+        .enumerate()
+        .map(|(index, adapter)| AdapterSummary::new(index, &adapter.get_info()))
+        .collect()
+}
+
+/// Rank of an adapter kind, lower first
+fn rank(device_type: wgpu::DeviceType, low_power: bool) -> u8 {
+    match device_type {
+        wgpu::DeviceType::DiscreteGpu => if low_power { 1 } else { 0 },
+        wgpu::DeviceType::IntegratedGpu => if low_power { 0 } else { 1 },
+        wgpu::DeviceType::VirtualGpu => 2,
+        wgpu::DeviceType::Other => 3,
+        wgpu::DeviceType::Cpu => 4,
+    }
+}
+
+/// Index of the adapter a preference picks, `None` if nothing matches
+///
+/// Software adapters are only chosen by name or index, or when there is
+/// nothing else.
+pub fn choose_adapter(adapters: &[AdapterSummary], preference: &AdapterPreference) -> Option<usize> {
+    let best = |low_power: bool| {
+        adapters
+            .iter()
+            .min_by_key(|a| (rank(a.device_type, low_power), a.index))
+            .map(|a| a.index)
+    };
+    match preference {
+        AdapterPreference::Auto => best(false),
+        AdapterPreference::LowPower => best(true),
+        AdapterPreference::Named(name) => {
+            let name = name.to_lowercase();
+            adapters.iter().find(|a| a.name.to_lowercase().contains(&name)).map(|a| a.index)
+        }
+        AdapterPreference::Index(index) => adapters.iter().find(|a| a.index == *index).map(|a| a.index),
+        AdapterPreference::Software => None,
+    }
+}
+
+/// Outcome of adapter selection
+#[derive(Debug)]
+pub enum AdapterSelection {
+    /// Render on this adapter
+    Gpu(wgpu::Adapter),
+    /// Render with the CPU rasterizer
+    Software,
+}
+
+/// Pick an adapter for the settings, able to present to `surface` if given
+///
+/// A name or index that matches nothing is logged and treated as
+/// [`AdapterPreference::Auto`].
+pub async fn select_adapter(
+    instance: &wgpu::Instance,
+    settings: &RenderSettings,
+    surface: Option<&wgpu::Surface<'_>>,
+) -> RenderResult<AdapterSelection> {
+    if settings.adapter == AdapterPreference::Software {
+        return Ok(AdapterSelection::Software);
+    }
+
+    let mut adapters: Vec<_> = instance.enumerate_adapters(settings.backends());
+    let summaries: Vec<_> = adapters
+        .iter()
+        .enumerate()
+        .filter(|(_, adapter)| surface.is_none_or(|s| adapter.is_surface_supported(s)))
+        .map(|(index, adapter)| AdapterSummary::new(index, &adapter.get_info()))
+        .collect();
+    let chosen = choose_adapter(&summaries, &settings.adapter).or_else(|| {
+        if settings.adapter != AdapterPreference::Auto && !summaries.is_empty() {
+            log::warn!("No GPU adapter matches {:?}, choosing automatically", settings.adapter);
+        }
+        choose_adapter(&summaries, &AdapterPreference::Auto)
+    });
+    if let Some(index) = chosen {
+        return Ok(AdapterSelection::Gpu(adapters.swap_remove(index)));
+    }
+
+    let fallback = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::None,
+            compatible_surface: surface,
+            force_fallback_adapter: true,
+        })
+        .await;
+    match fallback {
+        Some(adapter) => {
+            log::warn!("No GPU found, using software adapter {}", adapter.get_info().name);
+            Ok(AdapterSelection::Gpu(adapter))
+        }
+        None if settings.software_fallback && surface.is_none() => {
+            log::warn!("No graphics adapter found, rendering on the CPU");
+            Ok(AdapterSelection::Software)
+        }
+        None => Err(RenderError::NoAdapter),
+    }
+}
+
+/// An adapter and the device open on it, for renderers that do not select
+/// their own, such as one sharing an XR runtime's device
+pub struct OpenedDevice {
+    pub instance: wgpu::Instance,
+    pub adapter: wgpu::Adapter,
+    pub device: Arc<wgpu::Device>,
+    pub queue: Arc<wgpu::Queue>,
+}
+
+/// Device and queue with the features the pipelines use, as far as the
+/// adapter offers them
+pub async fn request_device(adapter: &wgpu::Adapter) -> RenderResult<(wgpu::Device, wgpu::Queue)> {
+    adapter
+        .request_device(&device_descriptor(adapter.features()), None)
+        .await
+        .map_err(|e| RenderError::DeviceRequest(e.to_string()))
+}
+
+/// Descriptor for a device on an adapter with `available` features
+pub(super) fn device_descriptor(available: wgpu::Features) -> wgpu::DeviceDescriptor<'static> {
+    let wanted = wgpu::Features::POLYGON_MODE_LINE
+        | wgpu::Features::MULTI_DRAW_INDIRECT
+        | wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+    wgpu::DeviceDescriptor {
+        label: Some("CADDY Device"),
+        required_features: wanted & available,
+        required_limits: wgpu::Limits {
+            max_texture_dimension_2d: 8192,
+            max_bind_groups: 4,
+            ..Default::default()
+        },
+    }
+}
+
+/// Flags a device as lost when the driver resets or the GPU goes away
+#[derive(Debug, Clone, Default)]
+pub struct DeviceMonitor {
+    lost: Arc<Mutex<Option<String>>>,
+}
+
+impl DeviceMonitor {
+    /// Watch a device; replaces any lost callback already set on it
+    pub fn watch(device: &wgpu::Device) -> Self {
+        let monitor = Self::default();
+        let lost = monitor.clone();
+        device.set_device_lost_callback(move |reason, message| {
+            // Dropping the device or replacing this callback is not a loss
+            if matches!(
+                reason,
+                wgpu::DeviceLostReason::Unknown | wgpu::DeviceLostReason::Destroyed
+            ) {
+                log::error!("GPU device lost ({:?}): {}", reason, message);
+                lost.mark_lost(&message);
+            }
+        });
+        let lost = monitor.clone();
+        device.on_uncaptured_error(Box::new(move |error| match error {
+            wgpu::Error::OutOfMemory { .. } => lost.mark_lost(&error.to_string()),
+            wgpu::Error::Validation { description, .. } => {
+                log::error!("GPU validation error: {}", description)
+            }
+        }));
+        monitor
+    }
+
+    /// Record a loss found some other way, such as a failed readback
+    pub fn mark_lost(&self, reason: &str) {
+        self.lost.lock().get_or_insert_with(|| reason.to_string());
+    }
+
+    /// Whether the device has been lost
+    pub fn is_lost(&self) -> bool {
+        self.lost.lock().is_some()
+    }
+
+    /// Why the device was lost
+    pub fn reason(&self) -> Option<String> {
+        self.lost.lock().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose_adapter() {
+        let adapter = |index: usize, name: &str, device_type| AdapterSummary {
+            index,
+            name: name.to_string(),
+            vendor: 0,
+            device: 0,
+            device_type,
+            backend: wgpu::Backend::Vulkan,
+        };
+        let adapters = [
+            adapter(0, "llvmpipe (LLVM 15.0.7, 256 bits)", wgpu::DeviceType::Cpu),
+            adapter(1, "Intel(R) UHD Graphics 770", wgpu::DeviceType::IntegratedGpu),
+            adapter(2, "NVIDIA RTX A4000", wgpu::DeviceType::DiscreteGpu),
+            adapter(3, "NVIDIA RTX A2000", wgpu::DeviceType::DiscreteGpu),
+        ];
+
+        assert_eq!(choose_adapter(&adapters, &AdapterPreference::Auto), Some(2));
+        assert_eq!(choose_adapter(&adapters, &AdapterPreference::LowPower), Some(1));
+        assert_eq!(choose_adapter(&adapters, &AdapterPreference::Named("a2000".into())), Some(3));
+        assert_eq!(choose_adapter(&adapters, &AdapterPreference::Named("radeon".into())), None);
+        assert_eq!(choose_adapter(&adapters, &AdapterPreference::Index(0)), Some(0));
+        assert_eq!(choose_adapter(&adapters, &AdapterPreference::Index(7)), None);
+        assert_eq!(choose_adapter(&adapters, &AdapterPreference::Software), None);
+        assert_eq!(choose_adapter(&adapters[..1], &AdapterPreference::Auto), Some(0));
+        assert!(adapters[0].is_software());
+        assert_eq!(adapters[2].label(), "NVIDIA RTX A4000 (Vulkan, DiscreteGpu)");
+
+        let monitor = DeviceMonitor::default();
+        assert!(!monitor.is_lost());
+        monitor.mark_lost("driver reset");
+        monitor.mark_lost("second report");
+        assert_eq!(monitor.reason().as_deref(), Some("driver reset"));
+    }
+}
@@ -243,6 +243,16 @@ impl Camera {
         self.ortho_height
     }
 
+    /// Get near clip distance
+    pub fn near(&self) -> f32 {
+        self.near
+    }
+
+    /// Get far clip distance
+    pub fn far(&self) -> f32 {
+        self.far
+    }
+
     /// Get distance to target
     pub fn distance(&self) -> f32 {
         (self.position - self.target).norm()
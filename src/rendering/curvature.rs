@@ -0,0 +1,183 @@
+//! Curvature comb overlay
+//!
+//! Draws the curvature analysis of splines and NURBS over the viewport so a
+//! curve's fairness can be judged before export: a comb of teeth along each
+//! curve, their length proportional to curvature, the envelope through the
+//! tooth tips, and a diamond marker at every inflection point. Bumps and
+//! flat spots that are invisible in the curve itself show up as kinks and
+//! dips in the envelope.
+//!
+//! The curves are analysed once when the overlay is built; only the vertex
+//! generation depends on the view. All curves in one overlay share a scale,
+//! so their combs can be compared, and the longest tooth is kept at
+//! [`CombStyle::length_px`] on screen at any zoom.
+
+use super::LineVertex;
+use crate::geometry::curvature::{Curvature, CurvatureAnalysis, Inflection};
+use crate::geometry::Point2D;
+
+/// Appearance of a curvature comb overlay
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CombStyle {
+    /// Teeth per knot span
+    pub samples_per_span: usize,
+    /// Screen length of the longest tooth, in pixels
+    pub length_px: f64,
+    /// Tooth color
+    pub comb_color: [f32; 4],
+    /// Envelope color
+    pub envelope_color: [f32; 4],
+    /// Inflection marker color
+    pub inflection_color: [f32; 4],
+    /// Inflection marker size, in pixels
+    pub marker_px: f64,
+    /// Line thickness
+    pub thickness: f32,
+}
+
+impl Default for CombStyle {
+    fn default() -> Self {
+        Self {
+            samples_per_span: 16,
+            length_px: 60.0,
+            comb_color: [0.3, 0.6, 1.0, 0.6],
+            envelope_color: [0.3, 0.6, 1.0, 1.0],
+            inflection_color: [1.0, 0.3, 0.2, 1.0],
+            marker_px: 6.0,
+            thickness: 1.0,
+        }
+    }
+}
+
+/// Curvature combs for a set of curves
+#[derive(Debug, Clone)]
+pub struct CurvatureComb {
+    /// Analysis of each curve
+    pub analyses: Vec<CurvatureAnalysis>,
+    /// Appearance
+    pub style: CombStyle,
+}
+
+impl CurvatureComb {
+    /// Analyse curves for display
+    pub fn new(curves: &[&dyn Curvature], style: CombStyle) -> Self {
+        let analyses = curves
+            .iter()
+            .map(|c| c.curvature_analysis(style.samples_per_span))
+            .collect();
+        Self::from_analyses(analyses, style)
+    }
+
+    /// Overlay for curves that have already been analysed
+    pub fn from_analyses(analyses: Vec<CurvatureAnalysis>, style: CombStyle) -> Self {
+        Self { analyses, style }
+    }
+
+    /// Largest absolute curvature over all curves
+    pub fn max_curvature(&self) -> f64 {
+        self.analyses.iter().map(|a| a.max_curvature()).fold(0.0, f64::max)
+    }
+
+    /// Comb scale, drawing units per unit of curvature, at a zoom level
+    pub fn scale(&self, pixel_size: f64) -> f64 {
+        let max = self.max_curvature();
+        if max > 0.0 {
+            self.style.length_px * pixel_size / max
+        } else {
+            0.0
+        }
+    }
+
+    /// Inflection points of all curves
+    pub fn inflections(&self) -> impl Iterator<Item = &Inflection> {
+        self.analyses.iter().flat_map(|a| a.inflections.iter())
+    }
+
+    /// Line-list vertices for the teeth, envelopes and inflection markers
+    /// at a zoom level (`pixel_size` drawing units per screen pixel)
+    pub fn vertices(&self, pixel_size: f64) -> Vec<LineVertex> {
+        let style = &self.style;
+        let scale = self.scale(pixel_size);
+        let mut out = Vec::new();
+        let mut line = |a: Point2D, b: Point2D, color: [f32; 4]| {
+            out.push(LineVertex::new([a.x as f32, a.y as f32, 0.0], color, style.thickness));
+            out.push(LineVertex::new([b.x as f32, b.y as f32, 0.0], color, style.thickness));
+        };
+
+        for analysis in &self.analyses {
+            if scale > 0.0 {
+                for (base, tip) in analysis.comb(scale) {
+                    if base.distance_to(&tip) > 0.0 {
+                        line(base, tip, style.comb_color);
+                    }
+                }
+                for pair in analysis.envelope(scale).windows(2) {
+                    line(pair[0], pair[1], style.envelope_color);
+                }
+            }
+
+            let r = style.marker_px * pixel_size * 0.5;
+            for inflection in &analysis.inflections {
+                let p = inflection.point;
+                let corners = [
+                    Point2D::new(p.x + r, p.y),
+                    Point2D::new(p.x, p.y + r),
+                    Point2D::new(p.x - r, p.y),
+                    Point2D::new(p.x, p.y - r),
+                ];
+                for i in 0..4 {
+                    line(corners[i], corners[(i + 1) % 4], style.inflection_color);
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{circle_to_nurbs, BezierCurve, Circle2D};
+
+    #[test]
+    fn test_combs_share_scale_and_mark_inflections() {
+        let circle = circle_to_nurbs(&Circle2D::new(Point2D::origin(), 2.0));
+        let s_curve = BezierCurve::new(vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(1.0, 1.0),
+            Point2D::new(2.0, -1.0),
+            Point2D::new(3.0, 0.0),
+        ]);
+        let style = CombStyle {
+            samples_per_span: 8,
+            ..CombStyle::default()
+        };
+        let comb = CurvatureComb::new(&[&circle, &s_curve], style);
+
+        assert_eq!(comb.inflections().count(), 1);
+
+        // The longest tooth is 60 px at any zoom
+        let pixel_size = 0.01;
+        let scale = comb.scale(pixel_size);
+        let longest = comb
+            .analyses
+            .iter()
+            .flat_map(|a| a.comb(scale))
+            .map(|(base, tip)| base.distance_to(&tip))
+            .fold(0.0, f64::max);
+        assert!((longest - 0.6).abs() < 1e-9);
+
+        // Circle teeth all have the same length
+        let lengths: Vec<f64> = comb.analyses[0]
+            .comb(scale)
+            .iter()
+            .map(|(b, t)| b.distance_to(t))
+            .collect();
+        assert!(lengths.iter().all(|l| (l - lengths[0]).abs() < 1e-9));
+
+        let vertices = comb.vertices(pixel_size);
+        assert_eq!(vertices.len() % 2, 0);
+        let markers = vertices.iter().filter(|v| v.color == style.inflection_color).count();
+        assert_eq!(markers, 8);
+    }
+}
@@ -0,0 +1,235 @@
+//! Draft analysis overlay
+//!
+//! Shades a solid by the draft of each face so a molded part can be checked
+//! before tooling: faces are colored by the legend band their draft angle
+//! falls in, and undercuts get their own color whatever their angle. The
+//! legend is configurable, from the default three bands (negative,
+//! insufficient, positive) to finer grades such as 0-1°, 1-3° and over 3°.
+//!
+//! The vertices are a flat-shaded triangle list in model coordinates, drawn
+//! with the mesh pipeline in place of the part's own shading; the legend
+//! entries carry each band's share of the surface for the panel beside the
+//! viewport.
+
+use super::MeshVertex;
+use crate::engine3d::draft::{DraftAnalysis, DraftClass, FaceDraft};
+
+/// One color band of the legend
+#[derive(Debug, Clone, PartialEq)]
+pub struct DraftBand {
+    /// Lower bound of the band, draft angle in degrees
+    pub from: f64,
+    /// Face color
+    pub color: [f32; 4],
+    /// Legend text
+    pub label: String,
+}
+
+/// Colors of a draft overlay
+#[derive(Debug, Clone, PartialEq)]
+pub struct DraftLegend {
+    /// Bands in ascending order of their lower bound; angles below the
+    /// first band use its color
+    pub bands: Vec<DraftBand>,
+    /// Color of undercut faces
+    pub undercut_color: [f32; 4],
+    /// Legend text for undercuts
+    pub undercut_label: String,
+}
+
+impl Default for DraftLegend {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl DraftLegend {
+    /// Negative, insufficient, and positive bands around a minimum draft in
+    /// degrees
+    pub fn new(min_draft: f64) -> Self {
+        let min_draft = min_draft.abs();
+        Self {
+            bands: vec![
+                DraftBand {
+                    from: -90.0,
+                    color: [0.2, 0.4, 1.0, 1.0],
+                    label: format!("Negative < -{}°", min_draft),
+                },
+                DraftBand {
+                    from: -min_draft,
+                    color: [1.0, 0.85, 0.1, 1.0],
+                    label: format!("Insufficient ±{}°", min_draft),
+                },
+                DraftBand {
+                    from: min_draft,
+                    color: [0.2, 0.8, 0.3, 1.0],
+                    label: format!("Positive > {}°", min_draft),
+                },
+            ],
+            undercut_color: [0.9, 0.1, 0.1, 1.0],
+            undercut_label: "Undercut".to_string(),
+        }
+    }
+
+    /// Add a band, keeping the bands in order
+    pub fn with_band(mut self, from: f64, color: [f32; 4], label: &str) -> Self {
+        let index = self.bands.partition_point(|b| b.from <= from);
+        self.bands.insert(
+            index,
+            DraftBand {
+                from,
+                color,
+                label: label.to_string(),
+            },
+        );
+        self
+    }
+
+    /// Index of the band a draft angle in degrees falls in
+    pub fn band_of(&self, angle: f64) -> Option<usize> {
+        if self.bands.is_empty() {
+            return None;
+        }
+        Some(self.bands.partition_point(|b| b.from <= angle).saturating_sub(1))
+    }
+}
+
+/// Legend line with the surface it covers
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegendEntry {
+    /// Legend text
+    pub label: String,
+    /// Swatch color
+    pub color: [f32; 4],
+    /// Area of the faces shown in this color
+    pub area: f64,
+    /// Fraction of the total surface, 0 to 1
+    pub share: f64,
+}
+
+/// Draft-shaded solid
+#[derive(Debug, Clone)]
+pub struct DraftOverlay {
+    /// Analysis being shown
+    pub analysis: DraftAnalysis,
+    /// Colors
+    pub legend: DraftLegend,
+}
+
+impl DraftOverlay {
+    /// Overlay for an analysed solid
+    pub fn new(analysis: DraftAnalysis, legend: DraftLegend) -> Self {
+        Self { analysis, legend }
+    }
+
+    /// Color of a face
+    pub fn face_color(&self, face: &FaceDraft) -> [f32; 4] {
+        if face.class == DraftClass::Undercut {
+            return self.legend.undercut_color;
+        }
+        self.legend
+            .band_of(face.angle.to_degrees())
+            .map_or([0.5, 0.5, 0.5, 1.0], |i| self.legend.bands[i].color)
+    }
+
+    /// Triangle-list vertices of every face in its legend color
+    pub fn vertices(&self) -> Vec<MeshVertex> {
+        let mut out = Vec::new();
+        for face in &self.analysis.faces {
+            let color = self.face_color(face);
+            let normal = [face.normal.x as f32, face.normal.y as f32, face.normal.z as f32];
+            for triangle in &face.triangles {
+                for p in triangle {
+                    out.push(MeshVertex::new(
+                        [p.x as f32, p.y as f32, p.z as f32],
+                        normal,
+                        color,
+                        [0.0, 0.0],
+                    ));
+                }
+            }
+        }
+        out
+    }
+
+    /// One entry per band, then undercuts, with the area each covers
+    pub fn legend_entries(&self) -> Vec<LegendEntry> {
+        let legend = &self.legend;
+        let mut areas = vec![0.0; legend.bands.len()];
+        let mut undercut = 0.0;
+        for face in &self.analysis.faces {
+            if face.class == DraftClass::Undercut {
+                undercut += face.area;
+            } else if let Some(i) = legend.band_of(face.angle.to_degrees()) {
+                areas[i] += face.area;
+            }
+        }
+
+        let total: f64 = self.analysis.faces.iter().map(|f| f.area).sum();
+        let entry = |label: &str, color: [f32; 4], area: f64| LegendEntry {
+            label: label.to_string(),
+            color,
+            area,
+            share: if total > 0.0 { area / total } else { 0.0 },
+        };
+        legend
+            .bands
+            .iter()
+            .zip(areas)
+            .map(|(band, area)| entry(&band.label, band.color, area))
+            .chain(std::iter::once(entry(
+                &legend.undercut_label,
+                legend.undercut_color,
+                undercut,
+            )))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Point3, Vector3};
+    use crate::engine3d::draft::DraftSettings;
+    use crate::engine3d::topology::ExtrudeOperation;
+
+    #[test]
+    fn test_shading_and_legend() {
+        // 10 x 10 x 2 plate with 2 degree walls
+        let square = vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(10.0, 0.0, 0.0),
+            Point3::new(10.0, 10.0, 0.0),
+            Point3::new(0.0, 10.0, 0.0),
+        ];
+        let mesh = ExtrudeOperation {
+            direction: Vector3::new(0.0, 0.0, 2.0),
+            draft: 2f64.to_radians(),
+            ..Default::default()
+        }
+        .extrude_profile(&square)
+        .unwrap();
+        let analysis = DraftAnalysis::analyze(&mesh, &DraftSettings::default()).unwrap();
+
+        // A grade between 1 and 3 degrees catches the walls
+        let legend = DraftLegend::new(1.0).with_band(3.0, [0.0, 0.5, 0.0, 1.0], "Positive > 3°");
+        assert_eq!(legend.band_of(2.0), Some(2));
+        assert_eq!(legend.band_of(45.0), Some(3));
+        assert_eq!(legend.band_of(-95.0), Some(0));
+
+        let overlay = DraftOverlay::new(analysis, legend);
+        let vertices = overlay.vertices();
+        assert_eq!(vertices.len() % 3, 0);
+        assert!(vertices.len() >= 3 * 12);
+        let wall_color = overlay.legend.bands[2].color;
+        assert_eq!(vertices.iter().filter(|v| v.color == wall_color).count(), 4 * 2 * 3);
+
+        let entries = overlay.legend_entries();
+        assert_eq!(entries.len(), 5);
+        assert!((entries.iter().map(|e| e.share).sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!((entries[0].area - 100.0).abs() < 1e-9);
+        assert_eq!(entries[1].area, 0.0);
+        assert_eq!(entries[4].label, "Undercut");
+        assert_eq!(entries[4].area, 0.0);
+    }
+}
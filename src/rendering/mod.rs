@@ -2,7 +2,21 @@
 //!
 //! This module provides a complete rendering system built on wgpu for cross-platform
 //! GPU acceleration. It handles multi-viewport rendering, camera management, and
-//! efficient rendering of CAD entities.
+//! efficient rendering of CAD entities, including point clouds drawn in
+//! culled chunks within a per-frame point budget, curvature comb
+//! overlays for judging spline fairness, and draft angle shading of molded
+//! parts with a configurable legend.
+//!
+//! The adapter is chosen in [`RenderSettings`], and a lost device is
+//! rebuilt on the next frame. Thumbnails render offscreen, on the CPU when
+//! a server or VM has no usable GPU, and [`PreviewService`] caches them
+//! per document and block until revision tracking shows them stale.
+//!
+//! For design review, viewports can render side-by-side stereo pairs, and
+//! [`Renderer::render_xr`] draws both eyes of a headset through an
+//! [`XrSession`], with controller navigation and measurement. With the
+//! `openxr` feature, `OpenXrSession` implements the session over an
+//! OpenXR runtime on Vulkan and opens the device the renderer shares.
 
 pub mod renderer;
 pub mod camera;
@@ -10,6 +24,19 @@ pub mod viewport;
 pub mod pipeline;
 pub mod shaders;
 pub mod buffers;
+pub mod patterns;
+pub mod pattern_gpu;
+pub mod pointcloud;
+pub mod curvature;
+pub mod draft;
+pub mod adapter;
+pub mod software;
+pub mod thumbnail;
+pub mod preview;
+pub mod stereo;
+pub mod xr;
+#[cfg(feature = "openxr")]
+pub mod xr_openxr;
 
 // Re-export main types
 pub use renderer::{Renderer, RenderContext, RenderMode};
@@ -17,6 +44,26 @@ pub use camera::{Camera, ProjectionType, CameraController, NamedView};
 pub use viewport::{Viewport, ViewportLayout, ViewportConfig};
 pub use pipeline::{LinePipeline, MeshPipeline, PointPipeline, TextPipeline, PipelineCache};
 pub use buffers::{VertexBuffer, IndexBuffer, UniformBuffer, DynamicBuffer};
+pub use patterns::{hatch_fill, HatchPattern, PatternLine, PatternView, Stroke, StrokeBatch};
+pub use pattern_gpu::{PatternBackend, PatternCompute, PatternOutput};
+pub use curvature::{CombStyle, CurvatureComb};
+pub use draft::{DraftBand, DraftLegend, DraftOverlay, LegendEntry};
+pub use adapter::{
+    choose_adapter, list_adapters, request_device, select_adapter, AdapterPreference, AdapterSelection,
+    AdapterSummary, DeviceMonitor, OpenedDevice, RenderSettings,
+};
+pub use software::SoftwareRasterizer;
+pub use thumbnail::Thumbnailer;
+pub use preview::{Preview, PreviewService, PreviewSize, PreviewSubject};
+pub use stereo::{eye_view_projection, Eye, StereoMode, StereoSettings};
+pub use xr::{
+    pick_triangles, ControllerState, Hand, XrFov, XrFrame, XrMeasure, XrNavigator, XrPose, XrSession, XrView,
+};
+#[cfg(feature = "openxr")]
+pub use xr_openxr::OpenXrSession;
+pub use pointcloud::{
+    CloudColorMode, CloudDrawStats, PointCloudBuffers, PointCloudStyle, DEFAULT_POINT_BUDGET,
+};
 
 use thiserror::Error;
 
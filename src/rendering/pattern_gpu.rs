@@ -0,0 +1,322 @@
+//! Compute-shader stroke generation for hatches and linetypes
+//!
+//! [`PatternCompute`] runs [`Shaders::pattern_compute_shader`] over a
+//! [`StrokeBatch`], one invocation per stroke, and writes line vertices
+//! straight into a [`PatternOutput`]. The vertex count lands in an indirect
+//! draw buffer, so nothing is read back: regenerating after a zoom is one
+//! small upload and one dispatch. [`PatternBackend::Cpu`] fills the same
+//! output from [`StrokeBatch::generate`] for adapters without compute
+//! support; fully headless callers use `generate` directly.
+//!
+//! An output holds a fixed number of vertices and drops segments past it,
+//! so batches that might not fit are [`split`](StrokeBatch::split) first.
+//! The [`Renderer`](super::Renderer) keeps the outputs of each viewport and
+//! regenerates them in [`update_patterns`](super::Renderer::update_patterns)
+//! whenever the viewport's [`PatternView`](super::PatternView) changes.
+
+use super::patterns::{Stroke, StrokeBatch};
+use super::shaders::Shaders;
+use super::*;
+use wgpu::util::DeviceExt;
+
+/// Invocations per workgroup (matches the shader)
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Floats per [`LineVertex`] in the shader's output array
+const VERTEX_FLOATS: usize = 11;
+
+/// Shader parameters
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct PatternParams {
+    stroke_count: u32,
+    capacity: u32,
+    _padding: [u32; 2],
+}
+
+/// Line vertices generated for a batch, drawn with the line pipeline
+pub struct PatternOutput {
+    vertices: wgpu::Buffer,
+    draw_args: wgpu::Buffer,
+    capacity: usize,
+}
+
+impl PatternOutput {
+    /// Allocate room for `capacity` vertices
+    pub fn new(device: &wgpu::Device, capacity: usize) -> Self {
+        let vertices = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Pattern Vertex Buffer"),
+            size: (capacity.max(2) * std::mem::size_of::<LineVertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let draw_args = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Pattern Draw Args"),
+            contents: bytemuck::cast_slice(&[0u32, 1, 0, 0]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::COPY_DST,
+        });
+
+        Self {
+            vertices,
+            draw_args,
+            capacity: capacity.max(2),
+        }
+    }
+
+    /// Replace the contents with vertices generated on the CPU, dropping
+    /// any past the capacity with a warning
+    pub fn upload(&self, queue: &wgpu::Queue, vertices: &[LineVertex]) {
+        if vertices.len() > self.capacity {
+            log::warn!(
+                "Pattern output holds {} vertices, {} dropped",
+                self.capacity,
+                vertices.len() - self.capacity
+            );
+        }
+        let vertices = &vertices[..vertices.len().min(self.capacity)];
+        queue.write_buffer(&self.vertices, 0, bytemuck::cast_slice(vertices));
+        queue.write_buffer(&self.draw_args, 0, bytemuck::cast_slice(&[vertices.len() as u32, 1, 0, 0]));
+    }
+
+    /// Draw the generated lines; the line pipeline must be bound
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_vertex_buffer(0, self.vertices.slice(..));
+        render_pass.draw_indirect(&self.draw_args, 0);
+    }
+
+    /// Vertex buffer
+    pub fn vertex_buffer(&self) -> &wgpu::Buffer {
+        &self.vertices
+    }
+
+    /// Indirect draw arguments (`vertex_count, instance_count, first_vertex, first_instance`)
+    pub fn draw_args_buffer(&self) -> &wgpu::Buffer {
+        &self.draw_args
+    }
+
+    /// Most vertices the output holds
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+/// Stroke generation compute pipeline
+pub struct PatternCompute {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl PatternCompute {
+    /// Create the compute pipeline
+    pub fn new(device: &wgpu::Device) -> RenderResult<Self> {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Pattern Compute Shader"),
+            source: wgpu::ShaderSource::Wgsl(Shaders::pattern_compute_shader().into()),
+        });
+
+        let storage = |binding: u32, read_only: bool| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Pattern Compute Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage(1, true),
+                storage(2, true),
+                storage(3, true),
+                storage(4, false),
+                storage(5, false),
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Pattern Compute Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Pattern Compute Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: "cs_main",
+        });
+
+        Ok(Self {
+            pipeline,
+            bind_group_layout,
+        })
+    }
+
+    /// Whether a device can run the shader
+    pub fn is_supported(device: &wgpu::Device) -> bool {
+        let limits = device.limits();
+        limits.max_compute_workgroup_size_x >= WORKGROUP_SIZE
+            && limits.max_compute_invocations_per_workgroup >= WORKGROUP_SIZE
+            && limits.max_storage_buffers_per_shader_stage >= 5
+    }
+
+    /// Record generation of a batch into `output`
+    ///
+    /// The output's vertex count is reset through `queue`, so the encoder
+    /// must be submitted after this call returns. Segments past the
+    /// output's capacity are dropped on the GPU; a warning is logged when
+    /// the batch's [`vertex_bound`](StrokeBatch::vertex_bound) says that
+    /// can happen.
+    pub fn dispatch(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        batch: &StrokeBatch,
+        output: &PatternOutput,
+    ) {
+        queue.write_buffer(&output.draw_args, 0, bytemuck::cast_slice(&[0u32, 1, 0, 0]));
+        if batch.is_empty() {
+            return;
+        }
+        let bound = batch.vertex_bound();
+        if bound > output.capacity {
+            log::warn!(
+                "Pattern batch may need {} vertices, output holds {}; the rest is dropped",
+                bound,
+                output.capacity
+            );
+        }
+
+        let params = PatternParams {
+            stroke_count: batch.strokes.len() as u32,
+            capacity: output.capacity as u32,
+            _padding: [0; 2],
+        };
+        // Bindings cannot be empty, so unused lists get one dummy entry
+        let edges: &[[f32; 4]] = if batch.edges.is_empty() { &[[0.0; 4]] } else { &batch.edges };
+        let dashes: &[f32] = if batch.dashes.is_empty() { &[0.0] } else { &batch.dashes };
+
+        let params = buffer_init(device, "Pattern Params", bytemuck::bytes_of(&params), wgpu::BufferUsages::UNIFORM);
+        let strokes = buffer_init(device, "Pattern Strokes", bytemuck::cast_slice::<Stroke, u8>(&batch.strokes), wgpu::BufferUsages::STORAGE);
+        let edges = buffer_init(device, "Pattern Edges", bytemuck::cast_slice(edges), wgpu::BufferUsages::STORAGE);
+        let dashes = buffer_init(device, "Pattern Dashes", bytemuck::cast_slice(dashes), wgpu::BufferUsages::STORAGE);
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Pattern Compute Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: params.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: strokes.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: edges.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: dashes.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 4, resource: output.vertices.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 5, resource: output.draw_args.as_entire_binding() },
+            ],
+        });
+
+        let (x, y) = workgroups(batch.strokes.len(), device.limits().max_compute_workgroups_per_dimension);
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Pattern Compute Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups(x, y, 1);
+    }
+}
+
+/// Where strokes are turned into line vertices
+pub enum PatternBackend {
+    /// Compute shader on the renderer's device
+    Gpu(PatternCompute),
+    /// [`StrokeBatch::generate`], uploaded to the output
+    Cpu,
+}
+
+impl PatternBackend {
+    /// Compute backend if the device supports it, CPU otherwise
+    pub fn new(device: &wgpu::Device) -> Self {
+        if !PatternCompute::is_supported(device) {
+            log::info!("Compute shaders unavailable, generating patterns on the CPU");
+            return Self::Cpu;
+        }
+        match PatternCompute::new(device) {
+            Ok(compute) => Self::Gpu(compute),
+            Err(e) => {
+                log::warn!("Pattern compute pipeline unavailable, using CPU: {}", e);
+                Self::Cpu
+            }
+        }
+    }
+
+    /// Whether generation runs on the GPU
+    pub fn is_gpu(&self) -> bool {
+        matches!(self, Self::Gpu(_))
+    }
+
+    /// Generate a batch into `output`; submit `encoder` before drawing it
+    pub fn generate(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        batch: &StrokeBatch,
+        output: &PatternOutput,
+    ) {
+        match self {
+            Self::Gpu(compute) => compute.dispatch(device, queue, encoder, batch, output),
+            Self::Cpu => output.upload(queue, &batch.generate(output.capacity)),
+        }
+    }
+}
+
+fn buffer_init(device: &wgpu::Device, label: &str, contents: &[u8], usage: wgpu::BufferUsages) -> wgpu::Buffer {
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some(label),
+        contents,
+        usage,
+    })
+}
+
+/// Workgroup grid covering `strokes` invocations, spilling into a second
+/// dimension past the per-dimension limit
+fn workgroups(strokes: usize, max_per_dimension: u32) -> (u32, u32) {
+    let groups = (strokes as u32).div_ceil(WORKGROUP_SIZE).max(1);
+    let x = groups.min(max_per_dimension.max(1));
+    (x, groups.div_ceil(x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gpu_layouts_match_shader() {
+        assert_eq!(std::mem::size_of::<Stroke>(), 80);
+        assert_eq!(std::mem::size_of::<LineVertex>(), VERTEX_FLOATS * 4);
+        assert_eq!(std::mem::size_of::<PatternParams>(), 16);
+    }
+
+    #[test]
+    fn test_workgroups_spill_into_second_dimension() {
+        assert_eq!(workgroups(0, 65535), (1, 1));
+        assert_eq!(workgroups(65, 65535), (2, 1));
+        // 10M strokes need 156,250 groups
+        let (x, y) = workgroups(10_000_000, 65535);
+        assert_eq!(x, 65535);
+        assert!(x * y * WORKGROUP_SIZE >= 10_000_000);
+    }
+}
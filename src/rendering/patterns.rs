@@ -0,0 +1,573 @@
+//! Hatch pattern and linetype stroke generation
+//!
+//! Hatches and non-continuous linetypes are drawn as many short line
+//! segments, and a dense hatch easily needs hundreds of thousands of them.
+//! Both are reduced here to the same primitive, a [`Stroke`]: a straight
+//! run along which a dash pattern is laid, optionally clipped against the
+//! boundary edges of a hatch (even-odd). A [`StrokeBatch`] collects the
+//! strokes for everything visible in a view; the batch is small (one
+//! stroke per hatch line or polyline segment) and the expensive part,
+//! intersecting boundaries and cutting dashes, runs either on the GPU
+//! ([`super::pattern_gpu`]) or on the CPU via [`StrokeBatch::generate`],
+//! which is the reference the compute shader follows.
+//!
+//! Strokes are built for a particular view so zooming regenerates them:
+//! hatch lines are only laid across the visible part of a hatch, pattern
+//! families spaced closer than [`MIN_SPACING_PX`] on screen are left out,
+//! and dash patterns shorter than [`MIN_PERIOD_PX`] are drawn solid.
+//!
+//! Solid hatches and filled regions are triangles rather than strokes;
+//! [`hatch_fill`] tessellates the boundary loops into an indexed mesh.
+
+use super::{LineVertex, MeshVertex};
+pub use crate::geometry::hatch::{HatchPattern, PatternLine, MAX_ROWS_PER_FAMILY};
+use crate::geometry::{tessellate, Point2D};
+use crate::io::document::{Hatch, Vec3};
+
+/// Pattern families whose lines are closer than this on screen are skipped
+pub const MIN_SPACING_PX: f64 = 2.0;
+
+/// Dash patterns whose period is shorter than this on screen draw solid
+pub const MIN_PERIOD_PX: f64 = 3.0;
+
+/// Most boundary crossings handled per hatch line (matches the shader)
+pub const MAX_CROSSINGS: usize = 64;
+
+/// Most pieces [`StrokeBatch::split`] cuts a single stroke into
+const MAX_PIECES: usize = 4096;
+
+/// The part of the drawing strokes are generated for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PatternView {
+    /// Visible region, lower-left corner
+    pub min: [f64; 2],
+    /// Visible region, upper-right corner
+    pub max: [f64; 2],
+    /// Drawing units per screen pixel
+    pub pixel_size: f64,
+}
+
+impl PatternView {
+    /// View of a region at a zoom level
+    pub fn new(min: [f64; 2], max: [f64; 2], pixel_size: f64) -> Self {
+        Self { min, max, pixel_size }
+    }
+
+    fn overlaps(&self, min: [f64; 2], max: [f64; 2]) -> bool {
+        min[0] <= self.max[0] && max[0] >= self.min[0] && min[1] <= self.max[1] && max[1] >= self.min[1]
+    }
+}
+
+/// A run along which a dash pattern is laid; GPU layout of the compute
+/// shader's `Stroke`
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Stroke {
+    /// Point at parameter 0
+    pub origin: [f32; 2],
+    /// Unit direction
+    pub direction: [f32; 2],
+    /// Parameter span to cover
+    pub range: [f32; 2],
+    /// Position in the dash pattern at parameter 0
+    pub phase: f32,
+    /// Length drawn for a dot
+    pub dot: f32,
+    /// Line color
+    pub color: [f32; 4],
+    /// Line thickness
+    pub thickness: f32,
+    /// First dash in the batch's dash list
+    pub dash_start: u32,
+    /// Number of dashes; 0 draws solid
+    pub dash_count: u32,
+    /// First boundary edge in the batch's edge list
+    pub edge_start: u32,
+    /// Number of boundary edges; 0 leaves the stroke unclipped
+    pub edge_count: u32,
+    pub _padding: [u32; 3],
+}
+
+/// Strokes for one view, with the dash and edge lists they index into
+#[derive(Debug, Clone, Default)]
+pub struct StrokeBatch {
+    /// Strokes
+    pub strokes: Vec<Stroke>,
+    /// Hatch boundary edges as `[x1, y1, x2, y2]`
+    pub edges: Vec<[f32; 4]>,
+    /// Dash lengths of all patterns
+    pub dashes: Vec<f32>,
+    /// Pattern families left out because they are too dense at this zoom
+    pub skipped_families: usize,
+}
+
+impl StrokeBatch {
+    /// Create an empty batch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether there is nothing to draw
+    pub fn is_empty(&self) -> bool {
+        self.strokes.is_empty()
+    }
+
+    /// Add the pattern lines of a hatch
+    pub fn add_hatch(
+        &mut self,
+        hatch: &Hatch,
+        pattern: &HatchPattern,
+        view: &PatternView,
+        color: [f32; 4],
+        thickness: f32,
+    ) {
+        let points = hatch.boundaries.iter().flatten();
+        let (min, max) = bounds(points.map(|p| [p.x, p.y]));
+        if !view.overlaps(min, max) {
+            return;
+        }
+        // Only the visible part of the hatch needs lines
+        let min = [min[0].max(view.min[0]), min[1].max(view.min[1])];
+        let max = [max[0].min(view.max[0]), max[1].min(view.max[1])];
+        let corners = [min, [max[0], min[1]], max, [min[0], max[1]]];
+
+        let edge_start = self.edges.len() as u32;
+        for boundary in hatch.active_boundaries().into_iter().filter(|b| b.len() > 2) {
+            for (i, p) in boundary.iter().enumerate() {
+                let q = &boundary[(i + 1) % boundary.len()];
+                self.edges.push([p.x as f32, p.y as f32, q.x as f32, q.y as f32]);
+            }
+        }
+        let edge_count = self.edges.len() as u32 - edge_start;
+        if edge_count == 0 {
+            return;
+        }
+
+        let scale = if hatch.scale > 0.0 { hatch.scale } else { 1.0 };
+        let (sin_h, cos_h) = hatch.angle.sin_cos();
+        for family in &pattern.lines {
+            let (sin, cos) = (family.angle + hatch.angle).sin_cos();
+            let (dir, normal) = ([cos, sin], [-sin, cos]);
+            let spacing = family.offset[1] * scale;
+            if spacing.abs() < view.pixel_size * MIN_SPACING_PX {
+                self.skipped_families += 1;
+                continue;
+            }
+            let origin = [
+                (family.origin[0] * cos_h - family.origin[1] * sin_h) * scale,
+                (family.origin[0] * sin_h + family.origin[1] * cos_h) * scale,
+            ];
+            let step = add(scale_vec(dir, family.offset[0] * scale), scale_vec(normal, spacing));
+
+            // Rows whose line crosses the visible region
+            let across = corners.map(|c| dot(sub(c, origin), normal) / spacing);
+            let first = across.iter().copied().fold(f64::INFINITY, f64::min).ceil() as i64;
+            let last = across.iter().copied().fold(f64::NEG_INFINITY, f64::max).floor() as i64;
+            let rows = (last - first + 1).clamp(0, MAX_ROWS_PER_FAMILY as i64);
+
+            let (dash_start, dash_count) = self.push_dashes(&family.dashes, scale, view);
+            for row in first..first + rows {
+                let row_origin = add(origin, scale_vec(step, row as f64));
+                let along = corners.map(|c| dot(sub(c, row_origin), dir));
+                self.strokes.push(Stroke {
+                    origin: to_f32(row_origin),
+                    direction: to_f32(dir),
+                    range: [
+                        along.iter().copied().fold(f64::INFINITY, f64::min) as f32,
+                        along.iter().copied().fold(f64::NEG_INFINITY, f64::max) as f32,
+                    ],
+                    phase: 0.0,
+                    dot: view.pixel_size as f32,
+                    color,
+                    thickness,
+                    dash_start,
+                    dash_count,
+                    edge_start,
+                    edge_count,
+                    _padding: [0; 3],
+                });
+            }
+        }
+    }
+
+    /// Add a polyline drawn with a linetype; the pattern runs on across
+    /// vertices rather than restarting at each one
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_polyline(
+        &mut self,
+        points: &[Vec3],
+        closed: bool,
+        pattern: &[f64],
+        scale: f64,
+        view: &PatternView,
+        color: [f32; 4],
+        thickness: f32,
+    ) {
+        let (dash_start, dash_count) = self.push_dashes(pattern, scale, view);
+        let closing = closed.then(|| (points.last(), points.first()));
+        let segments = points
+            .windows(2)
+            .map(|w| (w.first(), w.last()))
+            .chain(closing)
+            .filter_map(|(p, q)| Some(([p?.x, p?.y], [q?.x, q?.y])));
+
+        let mut distance = 0.0;
+        for (p, q) in segments {
+            let length = dot(sub(q, p), sub(q, p)).sqrt();
+            let (min, max) = bounds([p, q].into_iter());
+            if length > 0.0 && view.overlaps(min, max) {
+                self.strokes.push(Stroke {
+                    origin: to_f32(p),
+                    direction: to_f32(scale_vec(sub(q, p), 1.0 / length)),
+                    range: [0.0, length as f32],
+                    phase: distance as f32,
+                    dot: view.pixel_size as f32,
+                    color,
+                    thickness,
+                    dash_start,
+                    dash_count,
+                    edge_start: 0,
+                    edge_count: 0,
+                    _padding: [0; 3],
+                });
+            }
+            distance += length;
+        }
+    }
+
+    /// Line vertices for the batch, computed on the CPU
+    ///
+    /// Produces the same segments as the compute shader, in stroke order.
+    /// `capacity` caps the number of vertices like the GPU output buffer;
+    /// segments past it are dropped with a warning, so size it with
+    /// [`vertex_bound`](Self::vertex_bound) or [`split`](Self::split) first.
+    pub fn generate(&self, capacity: usize) -> Vec<LineVertex> {
+        let mut out = Vec::new();
+        let mut dropped = 0;
+        for stroke in &self.strokes {
+            let mut emit = |a: f32, b: f32| {
+                if out.len() + 2 > capacity {
+                    dropped += 1;
+                    return;
+                }
+                for t in [a, b] {
+                    let p = [stroke.origin[0] + stroke.direction[0] * t, stroke.origin[1] + stroke.direction[1] * t];
+                    out.push(LineVertex::new([p[0], p[1], 0.0], stroke.color, stroke.thickness));
+                }
+            };
+            for (a, b) in self.spans(stroke) {
+                dash_span(stroke, &self.dashes, a, b, &mut emit);
+            }
+        }
+        if dropped > 0 {
+            log::warn!("Pattern output full at {} vertices, {} segments dropped", capacity, dropped);
+        }
+        out
+    }
+
+    /// Most vertices the batch can generate
+    pub fn vertex_bound(&self) -> usize {
+        self.strokes.iter().fold(0, |sum, s| sum.saturating_add(self.stroke_bound(s)))
+    }
+
+    /// Split into batches that generate at most `max_vertices` vertices each
+    ///
+    /// Strokes too long for one batch are cut into pieces along their
+    /// range, which draw the same segments (a dash may end up in two
+    /// pieces). The batches share this batch's edge and dash lists.
+    pub fn split(&self, max_vertices: usize) -> Vec<StrokeBatch> {
+        let empty = || StrokeBatch {
+            strokes: Vec::new(),
+            edges: self.edges.clone(),
+            dashes: self.dashes.clone(),
+            skipped_families: 0,
+        };
+        let mut batches = Vec::new();
+        let mut current = empty();
+        let mut used = 0usize;
+        for stroke in &self.strokes {
+            for piece in self.cut(stroke, max_vertices) {
+                let bound = self.stroke_bound(&piece);
+                if used.saturating_add(bound) > max_vertices && !current.is_empty() {
+                    batches.push(std::mem::replace(&mut current, empty()));
+                    used = 0;
+                }
+                current.strokes.push(piece);
+                used = used.saturating_add(bound);
+            }
+        }
+        batches.push(current);
+        batches[0].skipped_families = self.skipped_families;
+        batches
+    }
+
+    /// Pieces of a stroke that each fit in `max_vertices`, as far as
+    /// [`MAX_PIECES`] allows
+    fn cut(&self, stroke: &Stroke, max_vertices: usize) -> Vec<Stroke> {
+        let bound = self.stroke_bound(stroke);
+        if bound <= max_vertices {
+            return vec![*stroke];
+        }
+        let pieces = bound.div_ceil((max_vertices / 2).max(1)).min(MAX_PIECES);
+        let [start, end] = stroke.range;
+        let length = (end - start) / pieces as f32;
+        let cuts: Vec<Stroke> = (0..pieces)
+            .map(|i| {
+                let to = if i + 1 == pieces { end } else { start + length * (i + 1) as f32 };
+                Stroke {
+                    range: [start + length * i as f32, to],
+                    ..*stroke
+                }
+            })
+            .collect();
+        if cuts.iter().any(|piece| self.stroke_bound(piece) > max_vertices) {
+            log::warn!("Stroke needs up to {} vertices and can't be cut to fit {}", bound, max_vertices);
+        }
+        cuts
+    }
+
+    /// Most vertices a stroke can generate: a segment per dash for every
+    /// period of the pattern, plus a partial period, on each span inside
+    /// the boundary
+    fn stroke_bound(&self, stroke: &Stroke) -> usize {
+        let spans = if stroke.edge_count == 0 {
+            1
+        } else {
+            (stroke.edge_count as usize).min(MAX_CROSSINGS) / 2
+        };
+        let pattern = &self.dashes[stroke.dash_start as usize..][..stroke.dash_count as usize];
+        let period: f32 = pattern.iter().map(|d| d.abs()).sum();
+        let segments = if pattern.is_empty() || period <= 0.0 {
+            1
+        } else {
+            let periods = ((stroke.range[1] - stroke.range[0]).max(0.0) / period).ceil() as usize;
+            periods.saturating_add(1).saturating_mul(pattern.len())
+        };
+        segments.saturating_mul(spans).saturating_mul(2)
+    }
+
+    /// Scaled dashes for a pattern, or none if it would draw solid anyway
+    fn push_dashes(&mut self, pattern: &[f64], scale: f64, view: &PatternView) -> (u32, u32) {
+        let period: f64 = pattern.iter().map(|d| d.abs()).sum::<f64>() * scale;
+        if pattern.is_empty() || period < view.pixel_size * MIN_PERIOD_PX {
+            return (0, 0);
+        }
+        let start = self.dashes.len() as u32;
+        self.dashes.extend(pattern.iter().map(|d| (d * scale) as f32));
+        (start, pattern.len() as u32)
+    }
+
+    /// Parameter spans of a stroke inside its boundary
+    fn spans(&self, stroke: &Stroke) -> Vec<(f32, f32)> {
+        if stroke.edge_count == 0 {
+            return vec![(stroke.range[0], stroke.range[1])];
+        }
+        let edges = &self.edges[stroke.edge_start as usize..][..stroke.edge_count as usize];
+        let normal = [-stroke.direction[1], stroke.direction[0]];
+        let mut hits = Vec::new();
+        for e in edges {
+            let dp = (e[0] - stroke.origin[0]) * normal[0] + (e[1] - stroke.origin[1]) * normal[1];
+            let dq = (e[2] - stroke.origin[0]) * normal[0] + (e[3] - stroke.origin[1]) * normal[1];
+            // Half-open test so a line through a vertex counts it once
+            if (dp > 0.0) != (dq > 0.0) && hits.len() < MAX_CROSSINGS {
+                let f = dp / (dp - dq);
+                let x = [e[0] + (e[2] - e[0]) * f, e[1] + (e[3] - e[1]) * f];
+                hits.push((x[0] - stroke.origin[0]) * stroke.direction[0] + (x[1] - stroke.origin[1]) * stroke.direction[1]);
+            }
+        }
+        hits.sort_by(f32::total_cmp);
+        hits.chunks_exact(2)
+            .map(|pair| (pair[0].max(stroke.range[0]), pair[1].min(stroke.range[1])))
+            .filter(|(a, b)| b > a)
+            .collect()
+    }
+}
+
+/// Filled triangles for a hatch, to draw with the mesh pipeline
+///
+/// The first boundary is the outer loop and the rest are holes, matching
+/// the even-odd clipping of hatch lines for islands one level deep.
+/// Vertices lie in the z = 0 plane facing +z, with drawing coordinates as
+/// texture coordinates.
+pub fn hatch_fill(hatch: &Hatch, color: [f32; 4]) -> (Vec<MeshVertex>, Vec<u32>) {
+    let loops: Vec<Vec<Point2D>> = hatch
+        .active_boundaries()
+        .into_iter()
+        .map(|b| b.iter().map(|p| Point2D::new(p.x, p.y)).collect())
+        .collect();
+    let Some((outer, holes)) = loops.split_first() else {
+        return (Vec::new(), Vec::new());
+    };
+    let mesh = tessellate(outer, holes);
+    let vertices = mesh
+        .positions()
+        .into_iter()
+        .map(|[x, y]| MeshVertex::new([x, y, 0.0], [0.0, 0.0, 1.0], color, [x, y]))
+        .collect();
+    (vertices, mesh.indices)
+}
+
+/// Lay the stroke's dash pattern over `[a, b]`
+fn dash_span(stroke: &Stroke, dashes: &[f32], a: f32, b: f32, emit: &mut impl FnMut(f32, f32)) {
+    let pattern = &dashes[stroke.dash_start as usize..][..stroke.dash_count as usize];
+    let period: f32 = pattern.iter().map(|d| d.abs()).sum();
+    if pattern.is_empty() || period <= 0.0 {
+        emit(a, b);
+        return;
+    }
+    let mut t = ((a + stroke.phase) / period).floor() * period - stroke.phase;
+    while t < b {
+        for &dash in pattern {
+            if dash > 0.0 {
+                let (lo, hi) = (t.max(a), (t + dash).min(b));
+                if hi > lo {
+                    emit(lo, hi);
+                }
+            } else if dash == 0.0 && t >= a && t < b {
+                emit(t, (t + stroke.dot).min(b));
+            }
+            t += dash.abs();
+        }
+    }
+}
+
+fn bounds(points: impl Iterator<Item = [f64; 2]>) -> ([f64; 2], [f64; 2]) {
+    points.fold(
+        ([f64::INFINITY; 2], [f64::NEG_INFINITY; 2]),
+        |(min, max), p| ([min[0].min(p[0]), min[1].min(p[1])], [max[0].max(p[0]), max[1].max(p[1])]),
+    )
+}
+
+fn add(a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    [a[0] + b[0], a[1] + b[1]]
+}
+
+fn sub(a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn scale_vec(a: [f64; 2], s: f64) -> [f64; 2] {
+    [a[0] * s, a[1] * s]
+}
+
+fn dot(a: [f64; 2], b: [f64; 2]) -> f64 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+fn to_f32(a: [f64; 2]) -> [f32; 2] {
+    [a[0] as f32, a[1] as f32]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(size: f64) -> Hatch {
+        let corners = [[0.0, 0.0], [size, 0.0], [size, size], [0.0, size]];
+        Hatch::new("LINE", vec![corners.iter().map(|c| Vec3::new(c[0], c[1], 0.0)).collect()])
+    }
+
+    fn segments(vertices: &[LineVertex]) -> Vec<([f32; 2], [f32; 2])> {
+        vertices
+            .chunks_exact(2)
+            .map(|v| ([v[0].position[0], v[0].position[1]], [v[1].position[0], v[1].position[1]]))
+            .collect()
+    }
+
+    #[test]
+    fn test_hatch_lines_clip_to_boundary_and_view() {
+        let view = PatternView::new([-100.0, -100.0], [100.0, 100.0], 0.01);
+        let mut hatch = square(31.75);
+        let mut batch = StrokeBatch::new();
+        batch.add_hatch(&hatch, &hatch.pattern_definition(), &view, [1.0; 4], 1.0);
+        let lines = segments(&batch.generate(usize::MAX));
+        // Rows at y = 0, 3.175, ... 28.575: the half-open crossing test
+        // keeps the row on the bottom edge and drops the one on the top
+        assert_eq!(lines.len(), 10);
+        assert!(lines.iter().all(|(a, b)| a[0].abs() < 1e-4 && (b[0] - 31.75).abs() < 1e-4));
+
+        // A hole from y = 1 to 30 cuts every row but the first in two
+        let hole = [[10.0, 1.0], [20.0, 1.0], [20.0, 30.0], [10.0, 30.0]];
+        hatch.boundaries.push(hole.iter().map(|c| Vec3::new(c[0], c[1], 0.0)).collect());
+        let mut batch = StrokeBatch::new();
+        batch.add_hatch(&hatch, &hatch.pattern_definition(), &view, [1.0; 4], 1.0);
+        assert_eq!(segments(&batch.generate(usize::MAX)).len(), 19);
+
+        // Zoomed into the lower-left corner only the visible rows (y = 0 and
+        // 3.175) are laid
+        let zoomed = PatternView::new([0.0, 0.0], [5.0, 5.0], 0.01);
+        let mut batch = StrokeBatch::new();
+        batch.add_hatch(&hatch, &hatch.pattern_definition(), &zoomed, [1.0; 4], 1.0);
+        assert_eq!(batch.strokes.len(), 2);
+
+        // Zoomed far out the lines would merge, so the family is skipped
+        let far = PatternView::new([-1e4, -1e4], [1e4, 1e4], 10.0);
+        let mut batch = StrokeBatch::new();
+        batch.add_hatch(&hatch, &hatch.pattern_definition(), &far, [1.0; 4], 1.0);
+        assert!(batch.is_empty());
+        assert_eq!(batch.skipped_families, 1);
+    }
+
+    #[test]
+    fn test_solid_fill_leaves_holes_open() {
+        let mut hatch = square(10.0);
+        let hole = [[2.0, 2.0], [2.0, 8.0], [8.0, 8.0], [8.0, 2.0]];
+        hatch.boundaries.push(hole.iter().map(|c| Vec3::new(c[0], c[1], 0.0)).collect());
+        let (vertices, indices) = hatch_fill(&hatch, [1.0; 4]);
+
+        assert_eq!(vertices.len(), 8);
+        assert_eq!(indices.len(), 8 * 3);
+        let area: f32 = indices
+            .chunks_exact(3)
+            .map(|t| {
+                let [a, b, c] = [t[0], t[1], t[2]].map(|i| vertices[i as usize].position);
+                ((b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])) / 2.0
+            })
+            .sum();
+        assert!((area - 64.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_linetype_dashes_continue_across_vertices() {
+        let view = PatternView::new([-10.0, -10.0], [10.0, 10.0], 0.001);
+        let points = [Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.75, 0.0, 0.0), Vec3::new(0.75, 1.0, 0.0)];
+        let mut batch = StrokeBatch::new();
+        batch.add_polyline(&points, false, &[0.5, -0.25], 1.0, &view, [1.0; 4], 1.0);
+        let dashes = segments(&batch.generate(usize::MAX));
+
+        // 0-0.5 on the first leg, then the pattern resumes at 0.75 along the
+        // path: 0.75-1.25 and 1.5-1.75 on the second leg
+        assert_eq!(dashes.len(), 3);
+        assert_eq!(dashes[1], ([0.75, 0.0], [0.75, 0.5]));
+        assert!((dashes[2].0[1] - 0.75).abs() < 1e-6 && (dashes[2].1[1] - 1.0).abs() < 1e-6);
+
+        // Too fine to see: drawn solid; and the capacity is respected
+        let far = PatternView::new([-10.0, -10.0], [10.0, 10.0], 1.0);
+        let mut batch = StrokeBatch::new();
+        batch.add_polyline(&points, true, &[0.5, -0.25], 1.0, &far, [1.0; 4], 1.0);
+        assert_eq!(segments(&batch.generate(usize::MAX)).len(), 3);
+        assert_eq!(batch.generate(4).len(), 4);
+    }
+
+    #[test]
+    fn test_split_keeps_every_segment() {
+        let view = PatternView::new([-10.0, -10.0], [110.0, 10.0], 0.001);
+        let points = [Vec3::new(0.0, 0.0, 0.0), Vec3::new(100.0, 0.0, 0.0)];
+        let mut batch = StrokeBatch::new();
+        batch.add_polyline(&points, false, &[0.5, -0.5], 1.0, &view, [1.0; 4], 1.0);
+        let whole = batch.generate(usize::MAX);
+        assert_eq!(whole.len(), 200);
+        assert!(batch.vertex_bound() >= whole.len());
+
+        let drawn = |v: &[LineVertex]| -> f32 { segments(v).iter().map(|(a, b)| b[0] - a[0]).sum() };
+        let parts = batch.split(64);
+        assert!(parts.len() > 1);
+        let mut total = 0.0;
+        for part in &parts {
+            assert!(part.vertex_bound() <= 64);
+            let vertices = part.generate(part.vertex_bound());
+            total += drawn(&vertices);
+        }
+        assert!((total - drawn(&whole)).abs() < 1e-3);
+    }
+}
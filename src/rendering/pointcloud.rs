@@ -0,0 +1,277 @@
+//! Chunked point cloud rendering
+//!
+//! A scan of millions of points is uploaded once, as one vertex buffer per
+//! [`CloudChunk`], and drawn with the point pipeline. Each frame the chunks
+//! outside the view frustum are skipped and a point budget is shared among
+//! the rest in proportion to their size; because points within a chunk are
+//! shuffled, drawing a prefix of a chunk's buffer shows an even sample of
+//! it, so a dense scan thins out uniformly instead of being cut off.
+//!
+//! Vertex positions are `f32` offsets from the cloud's origin, as stored in
+//! [`PointCloud`]; the model transform must translate by
+//! [`PointCloudBuffers::origin`] relative to the camera's own origin.
+
+use super::buffers::VertexBuffer;
+use super::LineVertex;
+use crate::core::primitives::Point3;
+use crate::geometry::pointcloud::{CloudChunk, PointCloud, CHUNK_POINTS};
+use std::sync::Arc;
+
+/// Points drawn per frame by default
+pub const DEFAULT_POINT_BUDGET: usize = 5_000_000;
+
+/// How points are colored
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CloudColorMode {
+    /// Scan colors; white where the scan has none
+    Rgb,
+    /// Grayscale by return intensity
+    Intensity,
+    /// Blue to red ramp from the lowest to the highest point
+    Elevation,
+    /// One color for every point
+    Uniform([f32; 4]),
+}
+
+/// Point cloud display settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointCloudStyle {
+    /// Point size in pixels
+    pub point_size: f32,
+    /// Coloring
+    pub color_mode: CloudColorMode,
+    /// Most points per chunk
+    pub chunk_points: usize,
+}
+
+impl Default for PointCloudStyle {
+    fn default() -> Self {
+        Self {
+            point_size: 2.0,
+            color_mode: CloudColorMode::Rgb,
+            chunk_points: CHUNK_POINTS,
+        }
+    }
+}
+
+/// What a frame drew
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CloudDrawStats {
+    /// Chunks inside the frustum
+    pub visible_chunks: usize,
+    /// Chunks culled
+    pub culled_chunks: usize,
+    /// Points drawn
+    pub points: usize,
+}
+
+/// Vertices for each chunk, positions relative to the cloud's origin
+pub fn chunk_vertices(
+    cloud: &PointCloud,
+    chunks: &[CloudChunk],
+    style: &PointCloudStyle,
+) -> Vec<Vec<LineVertex>> {
+    // Elevation ramp spans the whole cloud so chunks blend together
+    let (low, high) = cloud.positions.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), p| {
+        (low.min(p[2]), high.max(p[2]))
+    });
+    let vertex = |i: usize| {
+        let position = cloud.positions[i];
+        let color = match style.color_mode {
+            CloudColorMode::Rgb => cloud
+                .colors
+                .get(i)
+                .map_or([1.0; 4], |c| [c[0] as f32 / 255.0, c[1] as f32 / 255.0, c[2] as f32 / 255.0, 1.0]),
+            CloudColorMode::Intensity => {
+                let v = cloud.intensities.get(i).map_or(1.0, |&v| v as f32 / u16::MAX as f32);
+                [v, v, v, 1.0]
+            }
+            CloudColorMode::Elevation => {
+                let t = if high > low { (position[2] - low) / (high - low) } else { 0.5 };
+                [t, 1.0 - (2.0 * t - 1.0).abs(), 1.0 - t, 1.0]
+            }
+            CloudColorMode::Uniform(color) => color,
+        };
+        LineVertex::new(position, color, style.point_size)
+    };
+    chunks
+        .iter()
+        .map(|chunk| chunk.indices.iter().map(|&i| vertex(i as usize)).collect())
+        .collect()
+}
+
+/// Whether a box may be visible: false only if all eight corners lie
+/// outside the same clip plane
+///
+/// `view_proj` is column-major and maps the box's coordinates to clip
+/// space, with depth from 0 to 1 as in wgpu.
+pub fn box_in_frustum(min: [f32; 3], max: [f32; 3], view_proj: &[[f32; 4]; 4]) -> bool {
+    let mut outside = [true; 6];
+    for corner in 0..8 {
+        let p = [
+            if corner & 1 == 0 { min[0] } else { max[0] },
+            if corner & 2 == 0 { min[1] } else { max[1] },
+            if corner & 4 == 0 { min[2] } else { max[2] },
+        ];
+        let clip: [f32; 4] = std::array::from_fn(|row| {
+            view_proj[0][row] * p[0] + view_proj[1][row] * p[1] + view_proj[2][row] * p[2] + view_proj[3][row]
+        });
+        let [x, y, z, w] = clip;
+        let inside = [x >= -w, x <= w, y >= -w, y <= w, z >= 0.0, z <= w];
+        for (out, inside) in outside.iter_mut().zip(inside) {
+            *out &= !inside;
+        }
+    }
+    !outside.iter().any(|&o| o)
+}
+
+/// Share `budget` points among chunks of the given sizes, in proportion
+pub fn split_budget(counts: &[usize], budget: usize) -> Vec<usize> {
+    let total: usize = counts.iter().sum();
+    if total <= budget {
+        return counts.to_vec();
+    }
+    counts
+        .iter()
+        .map(|&count| ((count as u128 * budget as u128) / total as u128) as usize)
+        .collect()
+}
+
+struct GpuChunk {
+    buffer: VertexBuffer<LineVertex>,
+    min: [f32; 3],
+    max: [f32; 3],
+}
+
+/// A point cloud uploaded to the GPU in chunks
+pub struct PointCloudBuffers {
+    origin: [f64; 3],
+    chunks: Vec<GpuChunk>,
+}
+
+impl PointCloudBuffers {
+    /// Split `cloud` into chunks and upload them
+    pub fn new(device: Arc<wgpu::Device>, cloud: &PointCloud, style: &PointCloudStyle) -> Self {
+        let relative = |p: Point3| {
+            [
+                (p.x - cloud.origin[0]) as f32,
+                (p.y - cloud.origin[1]) as f32,
+                (p.z - cloud.origin[2]) as f32,
+            ]
+        };
+        let chunks = cloud.chunks(style.chunk_points);
+        let vertices = chunk_vertices(cloud, &chunks, style);
+        let chunks = chunks
+            .iter()
+            .zip(vertices)
+            .map(|(chunk, vertices)| GpuChunk {
+                buffer: VertexBuffer::new_with_data(device.clone(), "Point Cloud Chunk", &vertices),
+                min: relative(chunk.bounds.min),
+                max: relative(chunk.bounds.max),
+            })
+            .collect();
+        Self {
+            origin: cloud.origin,
+            chunks,
+        }
+    }
+
+    /// Position vertex coordinates are relative to
+    pub fn origin(&self) -> [f64; 3] {
+        self.origin
+    }
+
+    /// Number of chunks
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Total points uploaded
+    pub fn point_count(&self) -> usize {
+        self.chunks.iter().map(|c| c.buffer.count()).sum()
+    }
+
+    /// Draw the visible chunks within a point budget; the point pipeline
+    /// must be bound and `view_proj` must map cloud-local coordinates to
+    /// clip space
+    pub fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        view_proj: &[[f32; 4]; 4],
+        point_budget: usize,
+    ) -> CloudDrawStats {
+        let visible: Vec<&GpuChunk> = self
+            .chunks
+            .iter()
+            .filter(|c| box_in_frustum(c.min, c.max, view_proj))
+            .collect();
+        let counts: Vec<usize> = visible.iter().map(|c| c.buffer.count()).collect();
+        let shares = split_budget(&counts, point_budget);
+
+        let mut stats = CloudDrawStats {
+            visible_chunks: visible.len(),
+            culled_chunks: self.chunks.len() - visible.len(),
+            points: 0,
+        };
+        for (chunk, share) in visible.into_iter().zip(shares) {
+            if share == 0 {
+                continue;
+            }
+            render_pass.set_vertex_buffer(0, chunk.buffer.buffer().slice(..));
+            render_pass.draw(0..share as u32, 0..1);
+            stats.points += share;
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frustum_culling_and_budget() {
+        // Orthographic view of -10..10 in x and y, depth 0..10 looking down -z
+        let view_proj = [
+            [0.1, 0.0, 0.0, 0.0],
+            [0.0, 0.1, 0.0, 0.0],
+            [0.0, 0.0, -0.1, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        assert!(box_in_frustum([-1.0, -1.0, -1.0], [1.0, 1.0, 0.0], &view_proj));
+        // Straddling the edge of the view
+        assert!(box_in_frustum([9.0, 0.0, -1.0], [12.0, 1.0, 0.0], &view_proj));
+        assert!(!box_in_frustum([11.0, 0.0, -1.0], [12.0, 1.0, 0.0], &view_proj));
+        // Behind the camera
+        assert!(!box_in_frustum([0.0, 0.0, 1.0], [1.0, 1.0, 2.0], &view_proj));
+
+        assert_eq!(split_budget(&[100, 300], 1000), vec![100, 300]);
+        assert_eq!(split_budget(&[100, 300], 200), vec![50, 150]);
+        assert_eq!(split_budget(&[], 200), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_chunk_vertices() {
+        let mut cloud = PointCloud::new([1000.0, 2000.0, 0.0]);
+        cloud.push([1000.0, 2000.0, 0.0], Some([255, 0, 0]), Some(u16::MAX));
+        cloud.push([1001.0, 2000.0, 10.0], None, Some(0));
+        let chunks = cloud.chunks(CHUNK_POINTS);
+
+        let style = PointCloudStyle::default();
+        let mut vertices = chunk_vertices(&cloud, &chunks, &style).remove(0);
+        vertices.sort_by(|a, b| a.position[2].total_cmp(&b.position[2]));
+        assert_eq!(vertices[0].position, [0.0, 0.0, 0.0]);
+        assert_eq!(vertices[0].color, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(vertices[1].color, [1.0; 4]);
+        assert_eq!(vertices[1].thickness, 2.0);
+
+        let style = PointCloudStyle {
+            color_mode: CloudColorMode::Elevation,
+            ..style
+        };
+        let mut vertices = chunk_vertices(&cloud, &chunks, &style).remove(0);
+        vertices.sort_by(|a, b| a.position[2].total_cmp(&b.position[2]));
+        assert_eq!(vertices[0].color, [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(vertices[1].color, [1.0, 0.0, 0.0, 1.0]);
+    }
+}
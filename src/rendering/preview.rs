@@ -0,0 +1,367 @@
+//! Cached previews of drawings and their blocks
+//!
+//! [`PreviewService`] keeps the latest published state of each document and
+//! renders PNG thumbnails of it, and of its block definitions, at a few
+//! fixed sizes with a [`Thumbnailer`]. Each preview is stamped with the
+//! revision of what it shows and served from the cache until the
+//! document's revision tracking moves past it: a drawing preview lasts
+//! until the document version changes, a block preview until the block or
+//! a block nested in it is redefined.
+//!
+//! Drawings are flattened as for plotting, so hidden, frozen and
+//! non-plottable layers stay out and inserts are expanded. Pens too light
+//! to see on the white background draw black, and text is not drawn.
+//! Block previews don't follow layer changes.
+
+use super::camera::Camera;
+use super::thumbnail::Thumbnailer;
+use super::{LineVertex, RenderError, RenderResult, RenderSettings};
+use crate::io::document::{Color, Document, GeometryType, PaperSize};
+use crate::sheets::plot::{plot_document, PlotPage, PlotSettings};
+use image::{ImageOutputFormat, RgbaImage};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Deepest block nesting followed when stamping block previews, a guard
+/// against blocks that insert themselves
+const MAX_NESTING: usize = 16;
+
+/// Thumbnail sizes, all square
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewSize {
+    /// 64 pixels, for file lists
+    Small,
+    /// 256 pixels, for browsers and tooltips
+    #[default]
+    Medium,
+    /// 512 pixels, for detail panes
+    Large,
+}
+
+impl PreviewSize {
+    /// Every size, smallest first
+    pub const ALL: [PreviewSize; 3] = [PreviewSize::Small, PreviewSize::Medium, PreviewSize::Large];
+
+    /// Edge length in pixels
+    pub fn pixels(self) -> u32 {
+        match self {
+            PreviewSize::Small => 64,
+            PreviewSize::Medium => 256,
+            PreviewSize::Large => 512,
+        }
+    }
+}
+
+/// What a preview shows
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PreviewSubject {
+    /// The whole drawing
+    Document,
+    /// One block definition, by name
+    Block(String),
+}
+
+impl PreviewSubject {
+    /// Revision of `doc` a preview of this subject shows, `None` if the
+    /// document has no such block
+    pub fn revision(&self, doc: &Document) -> Option<u64> {
+        match self {
+            PreviewSubject::Document => Some(doc.version()),
+            PreviewSubject::Block(name) => block_revision(doc, name, 0),
+        }
+    }
+}
+
+/// Latest revision of a block or of any block nested in it
+fn block_revision(doc: &Document, name: &str, depth: usize) -> Option<u64> {
+    let block = doc.get_block(name)?;
+    let own = doc.block_revision(name).unwrap_or(0);
+    if depth >= MAX_NESTING {
+        return Some(own);
+    }
+    let nested = block.entities.iter().filter_map(|e| match &e.geometry {
+        GeometryType::Insert(insert) => block_revision(doc, &insert.block_name, depth + 1),
+        _ => None,
+    });
+    Some(nested.fold(own, u64::max))
+}
+
+/// A rendered thumbnail
+#[derive(Debug, Clone)]
+pub struct Preview {
+    /// Pixels, for display
+    pub image: Arc<RgbaImage>,
+    /// PNG encoding of `image`, for serving
+    pub png: Arc<Vec<u8>>,
+    /// Entity tag of the PNG for HTTP caching, quoted
+    pub etag: String,
+    /// Revision of the subject shown, see [`PreviewSubject::revision`]
+    pub revision: u64,
+}
+
+/// Cache key of a preview
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PreviewKey {
+    document: Uuid,
+    subject: PreviewSubject,
+    size: PreviewSize,
+}
+
+/// Renders and caches previews of published documents
+///
+/// Documents are published with [`publish`](Self::publish) whenever they
+/// are saved or otherwise reach a state worth showing; previews are only
+/// rendered when first asked for.
+pub struct PreviewService {
+    thumbnailer: Mutex<Thumbnailer>,
+    documents: RwLock<HashMap<Uuid, Arc<Document>>>,
+    cache: RwLock<HashMap<PreviewKey, Preview>>,
+}
+
+impl PreviewService {
+    /// Service rendering on the adapter the settings pick, or on the CPU
+    pub fn new(settings: RenderSettings) -> RenderResult<Self> {
+        Ok(Self {
+            thumbnailer: Mutex::new(Thumbnailer::new(settings)?),
+            documents: RwLock::new(HashMap::new()),
+            cache: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Make `doc` the current state of its document and drop the previews
+    /// it makes stale; returns how many were dropped
+    pub fn publish(&self, doc: Document) -> usize {
+        let mut documents = self.documents.write();
+        let mut cache = self.cache.write();
+        let before = cache.len();
+        cache.retain(|key, preview| {
+            key.document != doc.id || key.subject.revision(&doc) == Some(preview.revision)
+        });
+        let dropped = before - cache.len();
+        documents.insert(doc.id, Arc::new(doc));
+        dropped
+    }
+
+    /// Forget a document and its previews
+    pub fn remove(&self, document: Uuid) -> Option<Arc<Document>> {
+        let removed = self.documents.write().remove(&document);
+        self.cache.write().retain(|key, _| key.document != document);
+        removed
+    }
+
+    /// Current published state of a document
+    pub fn document(&self, document: Uuid) -> Option<Arc<Document>> {
+        self.documents.read().get(&document).cloned()
+    }
+
+    /// Number of previews cached
+    pub fn cached(&self) -> usize {
+        self.cache.read().len()
+    }
+
+    /// Preview of a published document or of one of its blocks, rendered
+    /// unless a current one is cached; `None` if either is unknown
+    pub fn preview(
+        &self,
+        document: Uuid,
+        subject: &PreviewSubject,
+        size: PreviewSize,
+    ) -> RenderResult<Option<Preview>> {
+        let Some(doc) = self.document(document) else {
+            return Ok(None);
+        };
+        let Some(revision) = subject.revision(&doc) else {
+            return Ok(None);
+        };
+        let key = PreviewKey {
+            document,
+            subject: subject.clone(),
+            size,
+        };
+        if let Some(preview) = self.cache.read().get(&key).filter(|p| p.revision == revision) {
+            return Ok(Some(preview.clone()));
+        }
+
+        let preview = self.render(&doc, subject, size)?;
+        // The document may have been republished while rendering
+        let documents = self.documents.read();
+        if documents.get(&document).and_then(|d| subject.revision(d)) == Some(revision) {
+            self.cache.write().insert(key, preview.clone());
+        }
+        Ok(Some(preview))
+    }
+
+    /// Render a preview of `doc` without caching it
+    pub fn render(
+        &self,
+        doc: &Document,
+        subject: &PreviewSubject,
+        size: PreviewSize,
+    ) -> RenderResult<Preview> {
+        let revision = subject.revision(doc).ok_or_else(|| {
+            RenderError::RenderFailure(format!("no {:?} in document {} to preview", subject, doc.id))
+        })?;
+        let page = match subject {
+            PreviewSubject::Document => plot(doc, size),
+            PreviewSubject::Block(name) => {
+                let mut sheet = doc.without_entities();
+                sheet.entities = doc.blocks[name].entities.clone();
+                plot(&sheet, size)
+            }
+        };
+        let lines = page.as_ref().map(preview_lines).unwrap_or_default();
+
+        let edge = size.pixels();
+        let half = edge as f32 / 2.0;
+        let mut camera = Camera::new_orthographic(
+            [half, half, 10.0].into(),
+            [half, half, 0.0].into(),
+            [0.0, 1.0, 0.0].into(),
+            1.0,
+        );
+        camera.set_ortho_height(edge as f32);
+        let image = self.thumbnailer.lock().render(&mut camera, &[], &lines, edge, edge)?;
+
+        let mut png = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+            .map_err(|e| RenderError::RenderFailure(format!("PNG encoding failed: {}", e)))?;
+        let etag = format!("\"{}\"", hex::encode(&Sha256::digest(&png)[..16]));
+        Ok(Preview {
+            image: Arc::new(image),
+            png: Arc::new(png),
+            etag,
+            revision,
+        })
+    }
+}
+
+/// Plot a drawing onto a square page of one millimeter per pixel, `None`
+/// if there is nothing to plot
+fn plot(doc: &Document, size: PreviewSize) -> Option<PlotPage> {
+    let edge = size.pixels() as f64;
+    let settings = PlotSettings {
+        paper: PaperSize::Custom {
+            width: edge,
+            height: edge,
+        },
+        margin: edge / 16.0,
+        line_width: 1.0,
+        ..PlotSettings::default()
+    };
+    // Fitting the extents only fails when there are none
+    plot_document(doc, &settings, "preview").ok()
+}
+
+/// Line list of a page's paths in paper millimeters
+fn preview_lines(page: &PlotPage) -> Vec<LineVertex> {
+    let mut lines = Vec::new();
+    for path in &page.paths {
+        let color = pen_color(path.color);
+        let vertex = |(x, y): (f64, f64)| LineVertex::new([x as f32, y as f32, 0.0], color, 1.0);
+        for pair in path.points.windows(2) {
+            lines.extend([vertex(pair[0]), vertex(pair[1])]);
+        }
+        if let (true, [first, .., last]) = (path.closed, path.points.as_slice()) {
+            lines.extend([vertex(*last), vertex(*first)]);
+        }
+    }
+    lines
+}
+
+/// Color a pen draws with on the white background; light pens, such as
+/// the white of layer 0, draw black
+fn pen_color(color: Color) -> [f32; 4] {
+    let [r, g, b] = [color.r, color.g, color.b].map(|c| c as f32 / 255.0);
+    if 0.2126 * r + 0.7152 * g + 0.0722 * b > 0.9 {
+        [0.0, 0.0, 0.0, 1.0]
+    } else {
+        [r, g, b, 1.0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{Block, Circle, Entity, Insert, Line, Vec3};
+    use crate::rendering::AdapterPreference;
+
+    #[test]
+    fn test_previews_follow_revisions() {
+        let mut doc = Document::new();
+        let line = doc.add_entity(Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::new(0.0, 0.0, 0.0),
+                end: Vec3::new(100.0, 100.0, 0.0),
+            }),
+            "0".to_string(),
+        ));
+        doc.add_block(Block {
+            name: "BOLT".to_string(),
+            base_point: Vec3::new(0.0, 0.0, 0.0),
+            entities: vec![Entity::new(
+                GeometryType::Circle(Circle {
+                    center: Vec3::new(0.0, 0.0, 0.0),
+                    radius: 5.0,
+                    normal: Vec3::unit_z(),
+                }),
+                "0".to_string(),
+            )],
+            description: String::new(),
+        });
+        doc.add_entity(Entity::new(
+            GeometryType::Insert(Insert {
+                block_name: "BOLT".to_string(),
+                position: Vec3::new(50.0, 0.0, 0.0),
+                scale: Vec3::new(1.0, 1.0, 1.0),
+                rotation: 0.0,
+                attributes: HashMap::new(),
+            }),
+            "0".to_string(),
+        ));
+        let id = doc.id;
+
+        let settings = RenderSettings {
+            adapter: AdapterPreference::Software,
+            ..RenderSettings::default()
+        };
+        let service = PreviewService::new(settings).unwrap();
+        assert_eq!(service.publish(doc.clone()), 0);
+
+        let drawing = service.preview(id, &PreviewSubject::Document, PreviewSize::Small).unwrap().unwrap();
+        assert_eq!(drawing.image.dimensions(), (64, 64));
+        // Layer 0's white pen draws black
+        assert!(drawing.image.pixels().any(|p| p.0 == [0, 0, 0, 255]));
+        assert!(drawing.png.starts_with(b"\x89PNG"));
+        let bolt = PreviewSubject::Block("BOLT".to_string());
+        let block = service.preview(id, &bolt, PreviewSize::Medium).unwrap().unwrap();
+        assert_eq!(block.image.dimensions(), (256, 256));
+        assert_eq!(service.cached(), 2);
+        let again = service.preview(id, &PreviewSubject::Document, PreviewSize::Small).unwrap().unwrap();
+        assert_eq!(again.etag, drawing.etag);
+        let missing = PreviewSubject::Block("NUT".to_string());
+        assert!(service.preview(id, &missing, PreviewSize::Small).unwrap().is_none());
+        let unknown = service.preview(Uuid::new_v4(), &PreviewSubject::Document, PreviewSize::Small);
+        assert!(unknown.unwrap().is_none());
+
+        // Editing the drawing leaves the block preview current
+        if let Some(GeometryType::Line(l)) = doc.get_entity_mut(line).map(|e| &mut e.geometry) {
+            l.end = Vec3::new(100.0, 0.0, 0.0);
+        }
+        assert_eq!(service.publish(doc.clone()), 1);
+        let edited = service.preview(id, &PreviewSubject::Document, PreviewSize::Small).unwrap().unwrap();
+        assert_ne!(edited.etag, drawing.etag);
+        assert_eq!(edited.revision, doc.version());
+
+        // Redefining the block stales both
+        doc.get_block_mut("BOLT").unwrap().base_point = Vec3::new(1.0, 0.0, 0.0);
+        assert_eq!(service.publish(doc), 2);
+        assert_eq!(service.cached(), 0);
+    }
+}
@@ -5,9 +5,26 @@ use camera::Camera;
 use viewport::{Viewport, ViewportLayout};
 use pipeline::PipelineCache;
 use buffers::UniformBuffer;
+use pattern_gpu::{PatternBackend, PatternOutput};
+use patterns::{PatternView, StrokeBatch};
+use stereo::{eye_view_projection, Eye, StereoMode, StereoSettings};
+use xr::{XrNavigator, XrSession};
 use std::sync::Arc;
 use parking_lot::RwLock;
 
+/// Background the frame is cleared to
+const CLEAR_COLOR: wgpu::Color = wgpu::Color {
+    r: 0.1,
+    g: 0.1,
+    b: 0.1,
+    a: 1.0,
+};
+
+/// Smallest and largest pattern outputs, in vertices; larger batches are
+/// split across several outputs
+const MIN_PATTERN_VERTICES: usize = 4096;
+const MAX_PATTERN_VERTICES: usize = 1 << 20;
+
 /// Rendering modes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RenderMode {
@@ -44,8 +61,89 @@ pub struct Renderer {
     msaa_view: Option<wgpu::TextureView>,
     transform_uniform: UniformBuffer<TransformUniforms>,
     light_uniform: UniformBuffer<LightUniforms>,
+    light: LightUniforms,
     bind_group_layout: wgpu::BindGroupLayout,
     bind_group: wgpu::BindGroup,
+    instance: wgpu::Instance,
+    settings: RenderSettings,
+    monitor: DeviceMonitor,
+    /// The device came from [`Renderer::with_device`] and cannot be reopened
+    shared_device: bool,
+    recoveries: u32,
+    generation: u32,
+    stereo: StereoSettings,
+    eye_targets: Option<EyeTargets>,
+    patterns: PatternBackend,
+    pattern_layers: Vec<Option<PatternLayer>>,
+}
+
+/// Hatch and linetype strokes generated for one viewport
+struct PatternLayer {
+    /// View the strokes were generated for; `None` once invalidated
+    view: Option<PatternView>,
+    /// One output per part of the split batch
+    outputs: Vec<PatternOutput>,
+}
+
+/// Depth and multisample textures sized for headset eye images
+struct EyeTargets {
+    size: (u32, u32),
+    _depth_texture: wgpu::Texture,
+    depth_view: wgpu::TextureView,
+    _msaa_texture: wgpu::Texture,
+    msaa_view: wgpu::TextureView,
+}
+
+/// Layout of the transform and light uniforms every pipeline binds as group 0
+pub(super) fn global_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Global Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+    })
+}
+
+/// Bind group over the transform and light uniforms
+pub(super) fn global_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    transform: &UniformBuffer<TransformUniforms>,
+    light: &UniformBuffer<LightUniforms>,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Global Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: transform.buffer().as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: light.buffer().as_entire_binding(),
+            },
+        ],
+    })
 }
 
 impl Renderer {
@@ -55,51 +153,56 @@ impl Renderer {
         width: u32,
         height: u32,
     ) -> RenderResult<Self> {
-        // Create wgpu instance
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            ..Default::default()
-        });
+        Self::with_settings(window, width, height, RenderSettings::default()).await
+    }
+
+    /// Initialize the renderer on the adapter the settings pick
+    pub async fn with_settings(
+        window: Arc<winit::window::Window>,
+        width: u32,
+        height: u32,
+        settings: RenderSettings,
+    ) -> RenderResult<Self> {
+        let instance = settings.instance();
 
         // Create surface
         let surface = instance
             .create_surface(window.clone())
             .map_err(|e| RenderError::SurfaceCreation(e.to_string()))?;
 
-        // Request adapter
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .ok_or(RenderError::NoAdapter)?;
+        let (adapter, device, queue) = Self::open_device(&instance, &settings, &surface).await?;
+        let opened = OpenedDevice { instance, adapter, device, queue };
+        Self::assemble(surface, width, height, settings, opened, false)
+    }
 
-        let adapter_info = adapter.get_info();
-        log::info!("Using GPU: {} ({:?})", adapter_info.name, adapter_info.backend);
-
-        // Request device and queue
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    label: Some("CADDY Device"),
-                    required_features: wgpu::Features::POLYGON_MODE_LINE
-                        | wgpu::Features::MULTI_DRAW_INDIRECT
-                        | wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
-                    required_limits: wgpu::Limits {
-                        max_texture_dimension_2d: 8192,
-                        max_bind_groups: 4,
-                        ..Default::default()
-                    },
-                },
-                None,
-            )
-            .await
-            .map_err(|e| RenderError::DeviceRequest(e.to_string()))?;
+    /// Initialize the renderer on a device opened elsewhere, such as the one
+    /// an XR runtime renders on; a lost device is not rebuilt
+    pub fn with_device(
+        window: Arc<winit::window::Window>,
+        width: u32,
+        height: u32,
+        settings: RenderSettings,
+        opened: OpenedDevice,
+    ) -> RenderResult<Self> {
+        let surface = opened
+            .instance
+            .create_surface(window)
+            .map_err(|e| RenderError::SurfaceCreation(e.to_string()))?;
+        Self::assemble(surface, width, height, settings, opened, true)
+    }
 
-        let device = Arc::new(device);
-        let queue = Arc::new(queue);
+    /// Configure the surface and build the targets, uniforms and pipelines
+    fn assemble(
+        surface: wgpu::Surface<'static>,
+        width: u32,
+        height: u32,
+        settings: RenderSettings,
+        opened: OpenedDevice,
+        shared_device: bool,
+    ) -> RenderResult<Self> {
+        let OpenedDevice { instance, adapter, device, queue } = opened;
+        let adapter_info = adapter.get_info();
+        let monitor = DeviceMonitor::watch(&device);
 
         // Configure surface
         let surface_caps = surface.get_capabilities(&adapter);
@@ -123,7 +226,7 @@ impl Renderer {
 
         surface.configure(&device, &surface_config);
 
-        let _context = RenderContext {
+        let context = RenderContext {
             device: device.clone(),
             queue: queue.clone(),
             surface,
@@ -132,68 +235,26 @@ impl Renderer {
         };
 
         // Create depth texture
-        let (depth_texture, depth_view) = Self::create_depth_texture(&device, width, height, 1);
-
-        // Create MSAA textures (4x)
+        // Create MSAA textures (4x); depth is multisampled to match
         let msaa_samples = 4;
+        let (depth_texture, depth_view) =
+            Self::create_depth_texture(&device, width, height, msaa_samples);
         let (msaa_texture, msaa_view) =
             Self::create_msaa_texture(&device, width, height, msaa_samples, surface_format);
 
         // Create uniform buffers
+        let light = LightUniforms::default();
         let transform_uniform = UniformBuffer::new(
             device.clone(),
             "Transform Uniform",
             TransformUniforms::default(),
         );
 
-        let light_uniform = UniformBuffer::new(
-            device.clone(),
-            "Light Uniform",
-            LightUniforms::default(),
-        );
-
-        // Create bind group layout
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("Global Bind Group Layout"),
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-            ],
-        });
+        let light_uniform = UniformBuffer::new(device.clone(), "Light Uniform", light);
 
-        // Create bind group
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Global Bind Group"),
-            layout: &bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: transform_uniform.buffer().as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: light_uniform.buffer().as_entire_binding(),
-                },
-            ],
-        });
+        let bind_group_layout = global_bind_group_layout(&device);
+        let bind_group =
+            global_bind_group(&device, &bind_group_layout, &transform_uniform, &light_uniform);
 
         // Initialize pipeline cache
         let pipeline_cache = Arc::new(RwLock::new(PipelineCache::new(
@@ -232,11 +293,111 @@ impl Renderer {
             msaa_view: Some(msaa_view),
             transform_uniform,
             light_uniform,
+            light,
             bind_group_layout,
             bind_group,
+            instance,
+            settings,
+            monitor,
+            shared_device,
+            recoveries: 0,
+            generation: 0,
+            stereo: StereoSettings::default(),
+            eye_targets: None,
+            patterns: PatternBackend::new(&device),
+            pattern_layers: Vec::new(),
         })
     }
 
+    /// Select an adapter for the surface and open a device on it
+    async fn open_device(
+        instance: &wgpu::Instance,
+        settings: &RenderSettings,
+        surface: &wgpu::Surface<'static>,
+    ) -> RenderResult<(wgpu::Adapter, Arc<wgpu::Device>, Arc<wgpu::Queue>)> {
+        let adapter = match select_adapter(instance, settings, Some(surface)).await? {
+            AdapterSelection::Gpu(adapter) => adapter,
+            AdapterSelection::Software => {
+                return Err(RenderError::InitializationError(
+                    "the CPU rasterizer renders offscreen only; a window needs an adapter".to_string(),
+                ))
+            }
+        };
+
+        let info = adapter.get_info();
+        log::info!("Using GPU: {} ({:?})", info.name, info.backend);
+
+        let (device, queue) = request_device(&adapter).await?;
+        Ok((adapter, Arc::new(device), Arc::new(queue)))
+    }
+
+    /// Rebuild the device, surface configuration, uniforms and pipelines
+    /// after the device was lost
+    ///
+    /// Buffers created on the old device are invalid afterwards; callers
+    /// recreate theirs when [`Renderer::generation`] changes.
+    pub fn recover(&mut self) -> RenderResult<()> {
+        if self.shared_device {
+            return Err(RenderError::RenderFailure(format!(
+                "shared GPU device lost: {}",
+                self.monitor.reason().unwrap_or_default()
+            )));
+        }
+        if self.recoveries >= self.settings.max_recoveries {
+            return Err(RenderError::RenderFailure(format!(
+                "GPU device lost {} times, giving up: {}",
+                self.recoveries,
+                self.monitor.reason().unwrap_or_default()
+            )));
+        }
+        self.recoveries += 1;
+        log::warn!(
+            "Rebuilding renderer after device loss ({}/{})",
+            self.recoveries,
+            self.settings.max_recoveries
+        );
+
+        let (adapter, device, queue) = futures::executor::block_on(Self::open_device(
+            &self.instance,
+            &self.settings,
+            &self.context.surface,
+        ))?;
+        self.monitor = DeviceMonitor::watch(&device);
+        self.context.surface.configure(&device, &self.context.surface_config);
+        self.context.adapter_info = adapter.get_info();
+        self.context.device = device.clone();
+        self.context.queue = queue;
+
+        let (width, height) = (self.context.surface_config.width, self.context.surface_config.height);
+        let format = self.context.surface_config.format;
+        (self.depth_texture, self.depth_view) =
+            Self::create_depth_texture(&device, width, height, self.msaa_samples);
+        let (msaa_texture, msaa_view) =
+            Self::create_msaa_texture(&device, width, height, self.msaa_samples, format);
+        self.msaa_texture = Some(msaa_texture);
+        self.msaa_view = Some(msaa_view);
+        self.eye_targets = None;
+
+        self.transform_uniform =
+            UniformBuffer::new(device.clone(), "Transform Uniform", TransformUniforms::default());
+        self.light_uniform = UniformBuffer::new(device.clone(), "Light Uniform", self.light);
+        self.bind_group_layout = global_bind_group_layout(&device);
+        self.bind_group = global_bind_group(
+            &device,
+            &self.bind_group_layout,
+            &self.transform_uniform,
+            &self.light_uniform,
+        );
+
+        // Swap in place so holders of the cache see the new pipelines
+        self.patterns = PatternBackend::new(&device);
+        self.pattern_layers.clear();
+        *self.pipeline_cache.write() =
+            PipelineCache::new(device, &self.bind_group_layout, format, self.msaa_samples)?;
+        self.generation += 1;
+        Ok(())
+    }
+
     /// Create depth texture
     fn create_depth_texture(
         device: &wgpu::Device,
@@ -307,7 +468,7 @@ impl Renderer {
 
             // Recreate depth texture
             let (depth_texture, depth_view) =
-                Self::create_depth_texture(&self.context.device, width, height, 1);
+                Self::create_depth_texture(&self.context.device, width, height, self.msaa_samples);
             self.depth_texture = depth_texture;
             self.depth_view = depth_view;
 
@@ -440,97 +601,278 @@ impl Renderer {
     where
         F: FnMut(&mut wgpu::RenderPass, &Viewport, &wgpu::BindGroup),
     {
-        // Get current surface texture
-        let output = self
-            .context
-            .surface
-            .get_current_texture()
-            .map_err(|e| RenderError::RenderFailure(e.to_string()))?;
+        if self.monitor.is_lost() {
+            self.recover()?;
+        }
+
+        // Get current surface texture; a lost or outdated surface is
+        // reconfigured and the frame skipped
+        let output = match self.context.surface.get_current_texture() {
+            Ok(output) => output,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.context
+                    .surface
+                    .configure(&self.context.device, &self.context.surface_config);
+                return Ok(());
+            }
+            Err(e) => return Err(RenderError::RenderFailure(e.to_string())),
+        };
 
         let view = output
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
-        // Create command encoder
-        let mut encoder = self
-            .context
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            });
+        // One pass per viewport, or per eye in stereo; each pass is
+        // submitted on its own so it sees its own transform
+        let mut passes = Vec::new();
+        for (index, viewport) in self.viewports.iter_mut().enumerate() {
+            let (x, y) = (viewport.x() as f32, viewport.y() as f32);
+            let (width, height) = (viewport.width() as f32, viewport.height() as f32);
+            match self.stereo.mode {
+                StereoMode::Mono => {
+                    let view_proj = viewport.camera_mut().view_projection_matrix();
+                    passes.push((index, [x, y, width, height], view_proj));
+                }
+                StereoMode::SideBySide => {
+                    let half = width / 2.0;
+                    for (slot, eye) in self.stereo.layout().into_iter().enumerate() {
+                        let view_proj = eye_view_projection(viewport.camera(), &self.stereo, eye);
+                        passes.push((index, [x + slot as f32 * half, y, half, height], view_proj));
+                    }
+                }
+            }
+        }
 
-        // Render each viewport
-        for viewport in &mut self.viewports {
-            // Update transform uniform
-            let view_proj = viewport.camera_mut().view_projection_matrix();
-            let mut transform = TransformUniforms::default();
-            transform.view_proj = view_proj;
+        for (i, (index, [x, y, width, height], view_proj)) in passes.into_iter().enumerate() {
+            let transform = TransformUniforms {
+                view_proj,
+                ..Default::default()
+            };
             self.transform_uniform.update(&self.context.queue, transform);
 
-            // Create render pass
-            let color_attachment = if self.msaa_samples > 1 {
-                wgpu::RenderPassColorAttachment {
-                    view: self.msaa_view.as_ref().unwrap(),
-                    resolve_target: Some(&view),
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.1,
-                            b: 0.1,
-                            a: 1.0,
+            let mut encoder = self
+                .context
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Render Encoder"),
+                });
+            {
+                // Only the first pass clears; later ones draw over it
+                let (color, resolve_target) = match &self.msaa_view {
+                    Some(msaa_view) if self.msaa_samples > 1 => (msaa_view, Some(&view)),
+                    _ => (&view, None),
+                };
+                let first = i == 0;
+                let cache = self.pipeline_cache.read();
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Main Render Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: color,
+                        resolve_target,
+                        ops: wgpu::Operations {
+                            load: if first { wgpu::LoadOp::Clear(CLEAR_COLOR) } else { wgpu::LoadOp::Load },
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                        view: &self.depth_view,
+                        depth_ops: Some(wgpu::Operations {
+                            load: if first { wgpu::LoadOp::Clear(1.0) } else { wgpu::LoadOp::Load },
+                            store: wgpu::StoreOp::Store,
                         }),
-                        store: wgpu::StoreOp::Store,
-                    },
+                        stencil_ops: None,
+                    }),
+                    ..Default::default()
+                });
+
+                render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+                render_fn(&mut render_pass, &self.viewports[index], &self.bind_group);
+
+                // Hatch and linetype strokes from the last update_patterns
+                if let Some(Some(layer)) = self.pattern_layers.get(index) {
+                    render_pass.set_pipeline(cache.line_pipeline().pipeline());
+                    render_pass.set_bind_group(0, &self.bind_group, &[]);
+                    for output in &layer.outputs {
+                        output.draw(&mut render_pass);
+                    }
                 }
-            } else {
-                wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.1,
-                            b: 0.1,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
+            }
+            self.context.queue.submit(std::iter::once(encoder.finish()));
+        }
+
+        output.present();
+
+        Ok(())
+    }
+
+    /// Regenerate hatch and linetype strokes for viewports whose view changed
+    ///
+    /// `build` collects the strokes for a view, typically with
+    /// [`StrokeBatch::add_hatch`] for each visible hatch. It is called only
+    /// for viewports panned or zoomed since their strokes were generated,
+    /// or for all of them after
+    /// [`invalidate_patterns`](Self::invalidate_patterns). Strokes are
+    /// generated by compute shader where the adapter supports it and on the
+    /// CPU otherwise, and [`render`](Self::render) draws them after each
+    /// viewport's own drawing. Batches too large for one output are split
+    /// across several. Returns the number of viewports regenerated.
+    pub fn update_patterns<F>(&mut self, mut build: F) -> usize
+    where
+        F: FnMut(&PatternView) -> StrokeBatch,
+    {
+        self.pattern_layers.resize_with(self.viewports.len(), || None);
+        let device = self.context.device.clone();
+        let mut encoder: Option<wgpu::CommandEncoder> = None;
+        let mut regenerated = 0;
+        for (viewport, slot) in self.viewports.iter().zip(&mut self.pattern_layers) {
+            let view = viewport.pattern_view();
+            if slot.as_ref().is_some_and(|layer| layer.view == Some(view)) {
+                continue;
+            }
+            let batches = build(&view).split(MAX_PATTERN_VERTICES);
+            let layer = slot.get_or_insert_with(|| PatternLayer {
+                view: None,
+                outputs: Vec::new(),
+            });
+            layer.outputs.truncate(batches.len());
+            let encoder = encoder.get_or_insert_with(|| {
+                device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Pattern Encoder"),
+                })
+            });
+            for (i, batch) in batches.iter().enumerate() {
+                let capacity = pattern_capacity(batch.vertex_bound());
+                match layer.outputs.get_mut(i) {
+                    Some(output) if output.capacity() >= capacity => {}
+                    Some(output) => *output = PatternOutput::new(&device, capacity),
+                    None => layer.outputs.push(PatternOutput::new(&device, capacity)),
                 }
-            };
+                self.patterns.generate(&device, &self.context.queue, encoder, batch, &layer.outputs[i]);
+            }
+            layer.view = Some(view);
+            regenerated += 1;
+        }
+        if let Some(encoder) = encoder {
+            self.context.queue.submit(std::iter::once(encoder.finish()));
+        }
+        regenerated
+    }
 
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Main Render Pass"),
-                color_attachments: &[Some(color_attachment)],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &self.depth_view,
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: wgpu::StoreOp::Store,
-                    }),
-                    stencil_ops: None,
-                }),
-                ..Default::default()
+    /// Regenerate every viewport's strokes on the next
+    /// [`update_patterns`](Self::update_patterns), e.g. after hatches or
+    /// linetypes in the drawing changed
+    pub fn invalidate_patterns(&mut self) {
+        for layer in self.pattern_layers.iter_mut().flatten() {
+            layer.view = None;
+        }
+    }
+
+    /// Whether strokes are generated by compute shader rather than on the CPU
+    pub fn patterns_on_gpu(&self) -> bool {
+        self.patterns.is_gpu()
+    }
+
+    /// Render a frame to a headset, if the runtime wants one
+    ///
+    /// Each eye is drawn through its tracked pose and field of view onto
+    /// the model as `navigator` places it in the room. Returns whether a
+    /// frame was rendered.
+    pub fn render_xr<S, F>(
+        &mut self,
+        session: &mut S,
+        navigator: &XrNavigator,
+        mut render_fn: F,
+    ) -> RenderResult<bool>
+    where
+        S: XrSession + ?Sized,
+        F: FnMut(&mut wgpu::RenderPass, Eye, &wgpu::BindGroup),
+    {
+        if self.monitor.is_lost() {
+            self.recover()?;
+        }
+        let format = self.context.surface_config.format;
+        if session.format() != format {
+            return Err(RenderError::RenderFailure(format!(
+                "XR swapchain format {:?} does not match the pipelines' {:?}",
+                session.format(),
+                format
+            )));
+        }
+        let Some(frame) = session.wait_frame()? else {
+            return Ok(false);
+        };
+
+        if self.eye_targets.as_ref().is_none_or(|t| t.size != frame.size) {
+            let (width, height) = frame.size;
+            let device = &self.context.device;
+            let (depth_texture, depth_view) =
+                Self::create_depth_texture(device, width, height, self.msaa_samples);
+            let (msaa_texture, msaa_view) =
+                Self::create_msaa_texture(device, width, height, self.msaa_samples, format);
+            self.eye_targets = Some(EyeTargets {
+                size: frame.size,
+                _depth_texture: depth_texture,
+                depth_view,
+                _msaa_texture: msaa_texture,
+                msaa_view,
             });
+        }
 
-            // Set viewport
-            render_pass.set_viewport(
-                viewport.x() as f32,
-                viewport.y() as f32,
-                viewport.width() as f32,
-                viewport.height() as f32,
-                0.0,
-                1.0,
-            );
+        for eye in Eye::BOTH {
+            let image = session.acquire_image(eye)?;
+            let transform = TransformUniforms {
+                view_proj: navigator.eye_view_projection(frame.view(eye)),
+                ..Default::default()
+            };
+            self.transform_uniform.update(&self.context.queue, transform);
 
-            // Call user render function
-            render_fn(&mut render_pass, viewport, &self.bind_group);
+            let Some(targets) = &self.eye_targets else {
+                break;
+            };
+            let mut encoder = self
+                .context
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("XR Eye Encoder"),
+                });
+            {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("XR Eye Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &targets.msaa_view,
+                        resolve_target: Some(&image),
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(CLEAR_COLOR),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                        view: &targets.depth_view,
+                        depth_ops: Some(wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(1.0),
+                            store: wgpu::StoreOp::Discard,
+                        }),
+                        stencil_ops: None,
+                    }),
+                    ..Default::default()
+                });
+                render_fn(&mut render_pass, eye, &self.bind_group);
+            }
+            self.context.queue.submit(std::iter::once(encoder.finish()));
         }
 
-        // Submit commands
-        self.context.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+        session.end_frame(&frame)?;
+        Ok(true)
+    }
 
-        Ok(())
+    /// Set how viewports are presented
+    pub fn set_stereo(&mut self, stereo: StereoSettings) {
+        self.stereo = stereo;
+    }
+
+    /// Stereo settings
+    pub fn stereo(&self) -> &StereoSettings {
+        &self.stereo
     }
 
     /// Get device
@@ -560,6 +902,32 @@ impl Renderer {
 
     /// Update light uniforms
     pub fn update_light(&mut self, light: LightUniforms) {
+        self.light = light;
         self.light_uniform.update(&self.context.queue, light);
     }
+
+    /// Adapter in use
+    pub fn adapter_info(&self) -> &wgpu::AdapterInfo {
+        &self.context.adapter_info
+    }
+
+    /// Settings the renderer was created with
+    pub fn settings(&self) -> &RenderSettings {
+        &self.settings
+    }
+
+    /// Number of times the device has been rebuilt; buffers created before
+    /// a change belong to a lost device
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+/// Vertices to allocate for a batch needing up to `vertices`, rounded up
+/// so small changes in the view don't reallocate
+fn pattern_capacity(vertices: usize) -> usize {
+    vertices
+        .checked_next_power_of_two()
+        .unwrap_or(MAX_PATTERN_VERTICES)
+        .clamp(MIN_PATTERN_VERTICES, MAX_PATTERN_VERTICES)
 }
@@ -510,6 +510,185 @@ fn vs_main(in: VertexInput) -> VertexOutput {
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     return in.color;
 }
+"#
+    }
+
+    /// Hatch and linetype stroke generation compute shader
+    ///
+    /// One invocation per stroke of a `StrokeBatch`; follows
+    /// `StrokeBatch::generate` and writes `LineVertex` pairs plus the
+    /// vertex count of an indirect draw.
+    pub fn pattern_compute_shader() -> &'static str {
+        r#"
+// Layout of patterns::Stroke
+struct Stroke {
+    origin: vec2<f32>,
+    direction: vec2<f32>,
+    range: vec2<f32>,
+    phase: f32,
+    dot_length: f32,
+    color: vec4<f32>,
+    thickness: f32,
+    dash_start: u32,
+    dash_count: u32,
+    edge_start: u32,
+    edge_count: u32,
+    _padding0: u32,
+    _padding1: u32,
+    _padding2: u32,
+}
+
+struct Params {
+    stroke_count: u32,
+    capacity: u32,
+    _padding0: u32,
+    _padding1: u32,
+}
+
+// wgpu::util::DrawIndirectArgs
+struct DrawArgs {
+    vertex_count: atomic<u32>,
+    instance_count: u32,
+    first_vertex: u32,
+    first_instance: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+@group(0) @binding(1)
+var<storage, read> strokes: array<Stroke>;
+
+@group(0) @binding(2)
+var<storage, read> edges: array<vec4<f32>>;
+
+@group(0) @binding(3)
+var<storage, read> dashes: array<f32>;
+
+// LineVertex as 11 floats: position, color, thickness, padding
+@group(0) @binding(4)
+var<storage, read_write> vertices: array<f32>;
+
+@group(0) @binding(5)
+var<storage, read_write> draw_args: DrawArgs;
+
+const VERTEX_FLOATS: u32 = 11u;
+const MAX_CROSSINGS: u32 = 64u;
+const WORKGROUP_SIZE: u32 = 64u;
+
+fn write_vertex(index: u32, s: Stroke, t: f32) {
+    let p = s.origin + s.direction * t;
+    let base = index * VERTEX_FLOATS;
+    vertices[base] = p.x;
+    vertices[base + 1u] = p.y;
+    vertices[base + 2u] = 0.0;
+    vertices[base + 3u] = s.color.r;
+    vertices[base + 4u] = s.color.g;
+    vertices[base + 5u] = s.color.b;
+    vertices[base + 6u] = s.color.a;
+    vertices[base + 7u] = s.thickness;
+    vertices[base + 8u] = 0.0;
+    vertices[base + 9u] = 0.0;
+    vertices[base + 10u] = 0.0;
+}
+
+fn emit(s: Stroke, a: f32, b: f32) {
+    let index = atomicAdd(&draw_args.vertex_count, 2u);
+    if (index + 2u > params.capacity) {
+        // Out of room: give the slot back so the draw count stays exact
+        atomicSub(&draw_args.vertex_count, 2u);
+        return;
+    }
+    write_vertex(index, s, a);
+    write_vertex(index + 1u, s, b);
+}
+
+// Lay the stroke's dash pattern over [a, b]
+fn dash_span(s: Stroke, a: f32, b: f32) {
+    var period = 0.0;
+    for (var i = 0u; i < s.dash_count; i = i + 1u) {
+        period = period + abs(dashes[s.dash_start + i]);
+    }
+    if (s.dash_count == 0u || period <= 0.0) {
+        emit(s, a, b);
+        return;
+    }
+
+    var t = floor((a + s.phase) / period) * period - s.phase;
+    loop {
+        if (t >= b) {
+            break;
+        }
+        for (var i = 0u; i < s.dash_count; i = i + 1u) {
+            let dash = dashes[s.dash_start + i];
+            if (dash > 0.0) {
+                let lo = max(t, a);
+                let hi = min(t + dash, b);
+                if (hi > lo) {
+                    emit(s, lo, hi);
+                }
+            } else if (dash == 0.0 && t >= a && t < b) {
+                emit(s, t, min(t + s.dot_length, b));
+            }
+            t = t + abs(dash);
+        }
+    }
+}
+
+@compute @workgroup_size(64)
+fn cs_main(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) groups: vec3<u32>,
+) {
+    let index = id.y * groups.x * WORKGROUP_SIZE + id.x;
+    if (index >= params.stroke_count) {
+        return;
+    }
+    let s = strokes[index];
+    if (s.edge_count == 0u) {
+        dash_span(s, s.range.x, s.range.y);
+        return;
+    }
+
+    // Crossings with the boundary, even-odd
+    var hits: array<f32, 64>;
+    var count = 0u;
+    let normal = vec2<f32>(-s.direction.y, s.direction.x);
+    for (var i = 0u; i < s.edge_count; i = i + 1u) {
+        let e = edges[s.edge_start + i];
+        let dp = dot(e.xy - s.origin, normal);
+        let dq = dot(e.zw - s.origin, normal);
+        // Half-open test so a line through a vertex counts it once
+        if ((dp > 0.0) != (dq > 0.0) && count < MAX_CROSSINGS) {
+            let f = dp / (dp - dq);
+            let x = e.xy + (e.zw - e.xy) * f;
+            hits[count] = dot(x - s.origin, s.direction);
+            count = count + 1u;
+        }
+    }
+
+    // Insertion sort; counts are small
+    for (var i = 1u; i < count; i = i + 1u) {
+        let v = hits[i];
+        var j = i;
+        loop {
+            if (j == 0u || hits[j - 1u] <= v) {
+                break;
+            }
+            hits[j] = hits[j - 1u];
+            j = j - 1u;
+        }
+        hits[j] = v;
+    }
+
+    for (var i = 0u; i + 1u < count; i = i + 2u) {
+        let a = max(hits[i], s.range.x);
+        let b = min(hits[i + 1u], s.range.y);
+        if (b > a) {
+            dash_span(s, a, b);
+        }
+    }
+}
 "#
     }
 }
@@ -530,6 +709,7 @@ mod tests {
         assert!(!Shaders::hidden_line_shader().is_empty());
         assert!(!Shaders::construction_shader().is_empty());
         assert!(!Shaders::axis_shader().is_empty());
+        assert!(!Shaders::pattern_compute_shader().is_empty());
     }
 
     #[test]
@@ -539,5 +719,6 @@ mod tests {
         assert!(Shaders::line_shader().contains("fs_main"));
         assert!(Shaders::mesh_shader().contains("vs_main"));
         assert!(Shaders::mesh_shader().contains("fs_main"));
+        assert!(Shaders::pattern_compute_shader().contains("cs_main"));
     }
 }
@@ -0,0 +1,253 @@
+//! CPU rasterizer for machines without a usable GPU
+//!
+//! Draws the same [`MeshVertex`] triangle lists and [`LineVertex`] line
+//! lists as the mesh and line pipelines, through the same view-projection
+//! matrix, into an RGBA image with a depth buffer. Triangles are lit from
+//! both sides with the ambient and diffuse terms of [`LightUniforms`];
+//! there is no anti-aliasing and lines are one pixel wide. It is meant for
+//! thumbnails and previews on headless servers and VMs, not for
+//! interactive frame rates.
+
+use super::*;
+use image::RgbaImage;
+
+/// Point in pixel coordinates with depth
+#[derive(Debug, Clone, Copy)]
+struct Projected {
+    x: f32,
+    y: f32,
+    depth: f32,
+}
+
+/// Depth-buffered rasterizer into an RGBA image
+pub struct SoftwareRasterizer {
+    width: u32,
+    height: u32,
+    color: RgbaImage,
+    depth: Vec<f32>,
+    view_proj: [[f32; 4]; 4],
+    light: LightUniforms,
+}
+
+impl SoftwareRasterizer {
+    /// Rasterizer with a cleared image of the given size
+    pub fn new(width: u32, height: u32) -> Self {
+        let (width, height) = (width.max(1), height.max(1));
+        Self {
+            width,
+            height,
+            color: RgbaImage::new(width, height),
+            depth: vec![f32::INFINITY; (width * height) as usize],
+            view_proj: TransformUniforms::default().view_proj,
+            light: LightUniforms::default(),
+        }
+    }
+
+    /// Fill with a color and reset the depth buffer
+    pub fn clear(&mut self, color: [f32; 4]) {
+        let pixel = image::Rgba(to_rgba8(color));
+        self.color.pixels_mut().for_each(|p| *p = pixel);
+        self.depth.fill(f32::INFINITY);
+    }
+
+    /// Set the view-projection matrix, column-major as in [`TransformUniforms`]
+    pub fn set_view_projection(&mut self, view_proj: [[f32; 4]; 4]) {
+        self.view_proj = view_proj;
+    }
+
+    /// Set the light
+    pub fn set_light(&mut self, light: LightUniforms) {
+        self.light = light;
+    }
+
+    /// Draw a triangle list
+    pub fn draw_triangles(&mut self, vertices: &[MeshVertex]) {
+        for triangle in vertices.chunks_exact(3) {
+            let Some(points) = triangle
+                .iter()
+                .map(|v| self.project(v.position))
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+            let colors: Vec<[f32; 4]> = triangle.iter().map(|v| self.shade(v)).collect();
+            self.fill_triangle([points[0], points[1], points[2]], [colors[0], colors[1], colors[2]]);
+        }
+    }
+
+    /// Draw a line list
+    pub fn draw_lines(&mut self, vertices: &[LineVertex]) {
+        for line in vertices.chunks_exact(2) {
+            let (Some(a), Some(b)) = (self.project(line[0].position), self.project(line[1].position)) else {
+                continue;
+            };
+            let steps = (b.x - a.x).abs().max((b.y - a.y).abs()).ceil().max(1.0) as usize;
+            for i in 0..=steps {
+                let t = i as f32 / steps as f32;
+                let color = lerp4(line[0].color, line[1].color, t);
+                // Lines win depth ties with the faces they outline
+                let depth = a.depth + (b.depth - a.depth) * t - 1e-4;
+                self.plot(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t, depth, color);
+            }
+        }
+    }
+
+    /// Image size in pixels
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Rendered image
+    pub fn image(&self) -> &RgbaImage {
+        &self.color
+    }
+
+    /// Take the rendered image
+    pub fn into_image(self) -> RgbaImage {
+        self.color
+    }
+
+    /// Pixel position and depth of a point, `None` behind the camera
+    fn project(&self, p: [f32; 3]) -> Option<Projected> {
+        let m = &self.view_proj;
+        let clip = |row: usize| m[0][row] * p[0] + m[1][row] * p[1] + m[2][row] * p[2] + m[3][row];
+        let w = clip(3);
+        if w <= f32::EPSILON {
+            return None;
+        }
+        let (x, y, z) = (clip(0) / w, clip(1) / w, clip(2) / w);
+        Some(Projected {
+            x: (x + 1.0) * 0.5 * self.width as f32,
+            y: (1.0 - y) * 0.5 * self.height as f32,
+            depth: z,
+        })
+    }
+
+    /// Lit color of a vertex
+    fn shade(&self, vertex: &MeshVertex) -> [f32; 4] {
+        let light = &self.light;
+        let to_light = [0, 1, 2].map(|i| light.light_position[i] - vertex.position[i]);
+        let diffuse = normalize(vertex.normal)
+            .zip(normalize(to_light))
+            .map_or(0.0, |(n, l)| (n[0] * l[0] + n[1] * l[1] + n[2] * l[2]).abs());
+        let intensity = light.ambient_strength + (1.0 - light.ambient_strength) * diffuse;
+        let c = vertex.color;
+        [
+            c[0] * intensity * light.light_color[0],
+            c[1] * intensity * light.light_color[1],
+            c[2] * intensity * light.light_color[2],
+            c[3],
+        ]
+    }
+
+    /// Fill a triangle, interpolating color and depth at pixel centers
+    fn fill_triangle(&mut self, [a, b, c]: [Projected; 3], colors: [[f32; 4]; 3]) {
+        let area = edge(&a, &b, c.x, c.y);
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+        let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as u32;
+        let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as u32;
+        let max_x = (a.x.max(b.x).max(c.x).ceil() as u32).min(self.width);
+        let max_y = (a.y.max(b.y).max(c.y).ceil() as u32).min(self.height);
+
+        for py in min_y..max_y {
+            for px in min_x..max_x {
+                let (x, y) = (px as f32 + 0.5, py as f32 + 0.5);
+                let wa = edge(&b, &c, x, y) / area;
+                let wb = edge(&c, &a, x, y) / area;
+                let wc = 1.0 - wa - wb;
+                if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                    continue;
+                }
+                let depth = wa * a.depth + wb * b.depth + wc * c.depth;
+                let color = [0, 1, 2, 3].map(|i| wa * colors[0][i] + wb * colors[1][i] + wc * colors[2][i]);
+                self.write(px, py, depth, color);
+            }
+        }
+    }
+
+    /// Plot a point of a line
+    fn plot(&mut self, x: f32, y: f32, depth: f32, color: [f32; 4]) {
+        if x >= 0.0 && y >= 0.0 && x < self.width as f32 && y < self.height as f32 {
+            self.write(x as u32, y as u32, depth, color);
+        }
+    }
+
+    /// Depth-test a pixel and blend it over what is there
+    fn write(&mut self, x: u32, y: u32, depth: f32, color: [f32; 4]) {
+        let index = (y * self.width + x) as usize;
+        if !(-1.0..=1.0).contains(&depth) || depth >= self.depth[index] {
+            return;
+        }
+        self.depth[index] = depth;
+        let pixel = self.color.get_pixel_mut(x, y);
+        let under = pixel.0.map(|v| v as f32 / 255.0);
+        let alpha = color[3].clamp(0.0, 1.0);
+        let blended = [0, 1, 2, 3].map(|i| {
+            let over = if i == 3 { 1.0 } else { color[i] };
+            over * alpha + under[i] * (1.0 - alpha)
+        });
+        *pixel = image::Rgba(to_rgba8(blended));
+    }
+}
+
+/// Twice the signed area of the triangle (a, b, p)
+fn edge(a: &Projected, b: &Projected, x: f32, y: f32) -> f32 {
+    (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+fn normalize(v: [f32; 3]) -> Option<[f32; 3]> {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    (length > f32::EPSILON).then(|| v.map(|c| c / length))
+}
+
+fn lerp4(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+fn to_rgba8(color: [f32; 4]) -> [u8; 4] {
+    color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_depth_and_lines() {
+        // Identity projection: clip space is world space
+        let mut raster = SoftwareRasterizer::new(40, 40);
+        raster.clear([0.0, 0.0, 0.0, 1.0]);
+        raster.set_light(LightUniforms {
+            light_position: [0.0, 0.0, 100.0],
+            ambient_strength: 0.0,
+            ..LightUniforms::default()
+        });
+
+        let quad = |z: f32, color: [f32; 4]| {
+            let corners = [[-0.5, -0.5], [0.5, -0.5], [0.5, 0.5], [-0.5, -0.5], [0.5, 0.5], [-0.5, 0.5]];
+            corners.map(|[x, y]| MeshVertex::new([x, y, z], [0.0, 0.0, 1.0], color, [0.0, 0.0]))
+        };
+        // The red square is nearer, whatever the drawing order
+        raster.draw_triangles(&quad(0.2, [1.0, 0.0, 0.0, 1.0]));
+        raster.draw_triangles(&quad(0.5, [0.0, 0.0, 1.0, 1.0]));
+        assert_eq!(raster.image().get_pixel(20, 20).0, [255, 0, 0, 255]);
+        assert_eq!(raster.image().get_pixel(2, 2).0, [0, 0, 0, 255]);
+
+        // A line on the square's surface is drawn over it
+        let green = [0.0, 1.0, 0.0, 1.0];
+        let line = [[-1.0, 0.0, 0.2], [1.0, 0.0, 0.2]].map(|p| LineVertex::new(p, green, 1.0));
+        raster.draw_lines(&line);
+        assert_eq!(raster.image().get_pixel(20, 20).0, [0, 255, 0, 255]);
+        assert_eq!(raster.image().get_pixel(0, 20).0, [0, 255, 0, 255]);
+
+        // Geometry behind the camera is skipped
+        let mut flipped = SoftwareRasterizer::new(4, 4);
+        let mut view_proj = TransformUniforms::default().view_proj;
+        view_proj[3][3] = -1.0;
+        flipped.set_view_projection(view_proj);
+        flipped.draw_triangles(&quad(0.2, green));
+        assert_eq!(flipped.image().get_pixel(2, 2).0, [0, 0, 0, 0]);
+    }
+}
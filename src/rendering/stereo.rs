@@ -0,0 +1,194 @@
+//! Stereo projection for design review
+//!
+//! Side-by-side stereo renders each viewport twice, once per eye, with the
+//! eyes spread along the camera's right axis. Perspective cameras use
+//! parallel eyes with off-axis frusta that converge at the camera target,
+//! so the target sits at screen depth and nearer parts stand out of the
+//! screen; orthographic cameras have no perspective to shift and turn each
+//! eye about the target instead. Each eye is squeezed into half the
+//! viewport at its full aspect ratio, as 3D displays and projectors expect.
+//!
+//! Matrices here put depth in 0..1 as wgpu clips it.
+
+use super::camera::{Camera, ProjectionType};
+use nalgebra::{Matrix4, Rotation3, Unit};
+use serde::{Deserialize, Serialize};
+
+/// One eye of a stereo pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Eye {
+    Left,
+    Right,
+}
+
+impl Eye {
+    /// Both eyes, left first
+    pub const BOTH: [Eye; 2] = [Eye::Left, Eye::Right];
+
+    /// -1 for the left eye, 1 for the right
+    pub fn sign(self) -> f32 {
+        match self {
+            Eye::Left => -1.0,
+            Eye::Right => 1.0,
+        }
+    }
+}
+
+/// How viewports are presented
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StereoMode {
+    /// One image per viewport
+    #[default]
+    Mono,
+    /// Left and right eye images side by side in each viewport
+    SideBySide,
+}
+
+/// Stereo viewing settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StereoSettings {
+    /// Presentation mode
+    pub mode: StereoMode,
+    /// Distance between the eyes as a fraction of the convergence distance;
+    /// about 1/30 is comfortable
+    pub separation: f32,
+    /// Distance from the camera that appears at screen depth, the distance
+    /// to the camera target when `None`
+    pub convergence: Option<f32>,
+    /// Put the right eye on the left, for cross-eyed free viewing
+    pub swap_eyes: bool,
+}
+
+impl Default for StereoSettings {
+    fn default() -> Self {
+        Self {
+            mode: StereoMode::Mono,
+            separation: 1.0 / 30.0,
+            convergence: None,
+            swap_eyes: false,
+        }
+    }
+}
+
+impl StereoSettings {
+    /// Eyes in the order they are laid out, left to right
+    pub fn layout(&self) -> [Eye; 2] {
+        if self.swap_eyes {
+            [Eye::Right, Eye::Left]
+        } else {
+            Eye::BOTH
+        }
+    }
+}
+
+/// View-projection matrix of one eye looking through `camera`, column-major
+/// as in [`super::TransformUniforms`]
+pub fn eye_view_projection(camera: &Camera, settings: &StereoSettings, eye: Eye) -> [[f32; 4]; 4] {
+    let position = camera.position();
+    let target = camera.target();
+    let convergence = settings.convergence.unwrap_or_else(|| camera.distance()).max(camera.near());
+    let half_separation = 0.5 * settings.separation * convergence;
+    let forward = (target - position).normalize();
+    let right = forward.cross(&camera.up()).normalize();
+    let (near, far) = (camera.near(), camera.far());
+
+    let matrix = match camera.projection_type() {
+        ProjectionType::Perspective => {
+            let offset = right * (eye.sign() * half_separation);
+            let view = Matrix4::look_at_rh(&(position + offset), &(target + offset), &camera.up());
+            let top = near * (camera.fov().to_radians() / 2.0).tan();
+            let half_width = top * camera.aspect_ratio();
+            // Shift the frustum towards the other eye so both meet at the
+            // convergence distance
+            let shift = -eye.sign() * half_separation * near / convergence;
+            frustum(-half_width + shift, half_width + shift, -top, top, near, far) * view
+        }
+        ProjectionType::Orthographic => {
+            let turn = eye.sign() * (half_separation / convergence).atan();
+            let rotation = Rotation3::from_axis_angle(&Unit::new_normalize(camera.up()), turn);
+            let eye_position = target + rotation * (position - target);
+            let view = Matrix4::look_at_rh(&eye_position, &target, &camera.up());
+            let top = camera.ortho_height() / 2.0;
+            let half_width = top * camera.aspect_ratio();
+            orthographic(-half_width, half_width, -top, top, near, far) * view
+        }
+    };
+    matrix.into()
+}
+
+/// Perspective projection of a view frustum bounded by `left`, `right`,
+/// `bottom` and `top` on the near plane, for a right-handed view space
+/// looking down -Z
+pub fn frustum(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Matrix4<f32> {
+    Matrix4::new(
+        2.0 * near / (right - left), 0.0, (right + left) / (right - left), 0.0,
+        0.0, 2.0 * near / (top - bottom), (top + bottom) / (top - bottom), 0.0,
+        0.0, 0.0, far / (near - far), near * far / (near - far),
+        0.0, 0.0, -1.0, 0.0,
+    )
+}
+
+/// Orthographic projection of a view box, for a right-handed view space
+/// looking down -Z
+pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Matrix4<f32> {
+    Matrix4::new(
+        2.0 / (right - left), 0.0, 0.0, -(right + left) / (right - left),
+        0.0, 2.0 / (top - bottom), 0.0, -(top + bottom) / (top - bottom),
+        0.0, 0.0, 1.0 / (near - far), near / (near - far),
+        0.0, 0.0, 0.0, 1.0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::{Point3, Vector3, Vector4};
+
+    fn project(view_proj: [[f32; 4]; 4], p: Point3<f32>) -> Vector3<f32> {
+        let clip = Matrix4::from(view_proj) * Vector4::new(p.x, p.y, p.z, 1.0);
+        clip.xyz() / clip.w
+    }
+
+    #[test]
+    fn test_eye_parallax() {
+        let mut camera = Camera::new_perspective(
+            Point3::new(0.0, 0.0, 30.0),
+            Point3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            1.5,
+            45.0,
+        );
+        let settings = StereoSettings {
+            mode: StereoMode::SideBySide,
+            ..Default::default()
+        };
+        let [left, right] = Eye::BOTH.map(|eye| eye_view_projection(&camera, &settings, eye));
+
+        // The target is at screen depth in both eyes
+        let target = camera.target();
+        assert!((project(left, target) - project(right, target)).norm() < 1e-5);
+        assert!(project(left, target).x.abs() < 1e-5);
+        // Nearer points are seen further right by the left eye: crossed
+        // parallax, in front of the screen; farther points the other way
+        let near = Point3::new(0.0, 0.0, 10.0);
+        assert!(project(left, near).x > project(right, near).x);
+        let far = Point3::new(0.0, 0.0, -20.0);
+        assert!(project(left, far).x < project(right, far).x);
+        // Depth runs from 0 at the near plane to 1 at the far plane
+        let z = |d: f32| project(left, Point3::new(0.0, 0.0, 30.0 - d)).z;
+        assert!(z(camera.near()).abs() < 1e-4);
+        assert!((z(camera.far()) - 1.0).abs() < 1e-4);
+
+        // Orthographic eyes turn about the target
+        camera.set_projection_type(ProjectionType::Orthographic);
+        let [left, right] = Eye::BOTH.map(|eye| eye_view_projection(&camera, &settings, eye));
+        assert!((project(left, target) - project(right, target)).norm() < 1e-5);
+        assert!(project(left, near).x > project(right, near).x);
+
+        let swapped = StereoSettings {
+            swap_eyes: true,
+            ..settings
+        };
+        assert_eq!(swapped.layout(), [Eye::Right, Eye::Left]);
+    }
+}
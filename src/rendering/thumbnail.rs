@@ -0,0 +1,369 @@
+//! Offscreen thumbnails on whatever hardware is available
+//!
+//! [`Thumbnailer`] renders triangles and edges to an image without a
+//! window, for document previews generated on a server. It draws with the
+//! regular mesh and line pipelines on the adapter [`select_adapter`] picks,
+//! and with the [`SoftwareRasterizer`] when there is none. A device lost
+//! mid-render is reopened, up to [`RenderSettings::max_recoveries`] times,
+//! after which the thumbnailer stays on the CPU.
+
+use super::buffers::UniformBuffer;
+use super::camera::Camera;
+use super::pipeline::PipelineCache;
+use super::renderer::{global_bind_group, global_bind_group_layout};
+use super::*;
+use futures::executor::block_on;
+use image::RgbaImage;
+use std::sync::Arc;
+use wgpu::util::DeviceExt;
+
+/// Color format of GPU thumbnails, matching the sRGB window surfaces
+const THUMBNAIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Device, uniforms and pipelines for offscreen rendering
+struct GpuTarget {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    adapter_info: wgpu::AdapterInfo,
+    monitor: DeviceMonitor,
+    transform_uniform: UniformBuffer<TransformUniforms>,
+    light_uniform: UniformBuffer<LightUniforms>,
+    bind_group: wgpu::BindGroup,
+    pipelines: PipelineCache,
+}
+
+impl GpuTarget {
+    fn new(adapter: &wgpu::Adapter) -> RenderResult<Self> {
+        let (device, queue) = block_on(request_device(adapter))?;
+        let (device, queue) = (Arc::new(device), Arc::new(queue));
+        let monitor = DeviceMonitor::watch(&device);
+
+        let transform_uniform =
+            UniformBuffer::new(device.clone(), "Thumbnail Transform", TransformUniforms::default());
+        let light_uniform = UniformBuffer::new(device.clone(), "Thumbnail Light", LightUniforms::default());
+        let layout = global_bind_group_layout(&device);
+        let bind_group = global_bind_group(&device, &layout, &transform_uniform, &light_uniform);
+        let pipelines = PipelineCache::new(device.clone(), &layout, THUMBNAIL_FORMAT, 1)?;
+
+        Ok(Self {
+            device,
+            queue,
+            adapter_info: adapter.get_info(),
+            monitor,
+            transform_uniform,
+            light_uniform,
+            bind_group,
+            pipelines,
+        })
+    }
+
+    /// Draw into a texture and read it back
+    fn render(
+        &self,
+        frame: &Frame,
+        triangles: &[MeshVertex],
+        lines: &[LineVertex],
+    ) -> RenderResult<RgbaImage> {
+        let device = &self.device;
+        // Camera matrices put depth in -1..1; wgpu clips to 0..1
+        let mut view_proj = frame.view_proj;
+        for column in &mut view_proj {
+            column[2] = 0.5 * (column[2] + column[3]);
+        }
+        let transform = TransformUniforms {
+            view_proj,
+            ..TransformUniforms::default()
+        };
+        self.transform_uniform.update(&self.queue, transform);
+        self.light_uniform.update(&self.queue, frame.light);
+
+        let size = wgpu::Extent3d {
+            width: frame.width,
+            height: frame.height,
+            depth_or_array_layers: 1,
+        };
+        let texture = |label, format, usage| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage,
+                view_formats: &[],
+            })
+        };
+        let color = texture(
+            "Thumbnail Color",
+            THUMBNAIL_FORMAT,
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        );
+        let depth = texture(
+            "Thumbnail Depth",
+            wgpu::TextureFormat::Depth32Float,
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+        );
+        let color_view = color.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_view = depth.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let vertex_buffer = |label, contents: &[u8]| {
+            (!contents.is_empty()).then(|| {
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(label),
+                    contents,
+                    usage: wgpu::BufferUsages::VERTEX,
+                })
+            })
+        };
+        let triangle_buffer = vertex_buffer("Thumbnail Triangles", bytemuck::cast_slice(triangles));
+        let line_buffer = vertex_buffer("Thumbnail Lines", bytemuck::cast_slice(lines));
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Thumbnail Encoder"),
+        });
+        {
+            let [r, g, b, a] = frame.background.map(f64::from);
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Thumbnail Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &color_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color { r, g, b, a }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                ..Default::default()
+            });
+            render_pass.set_bind_group(0, &self.bind_group, &[]);
+            if let Some(buffer) = &triangle_buffer {
+                render_pass.set_pipeline(self.pipelines.mesh_pipeline().pipeline());
+                render_pass.set_vertex_buffer(0, buffer.slice(..));
+                render_pass.draw(0..triangles.len() as u32, 0..1);
+            }
+            if let Some(buffer) = &line_buffer {
+                render_pass.set_pipeline(self.pipelines.line_pipeline().pipeline());
+                render_pass.set_vertex_buffer(0, buffer.slice(..));
+                render_pass.draw(0..lines.len() as u32, 0..1);
+            }
+        }
+
+        // Rows of a texture copy are padded to 256 bytes
+        let row_bytes = frame.width * 4;
+        let padded_row_bytes = row_bytes.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Thumbnail Readback"),
+            size: (padded_row_bytes * frame.height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            color.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_bytes),
+                    rows_per_image: None,
+                },
+            },
+            size,
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        if let Some(reason) = self.monitor.reason() {
+            return Err(RenderError::RenderFailure(format!("GPU device lost: {}", reason)));
+        }
+        match receiver.recv() {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => return Err(RenderError::RenderFailure(e.to_string())),
+            Err(e) => return Err(RenderError::RenderFailure(e.to_string())),
+        }
+
+        let mapped = slice.get_mapped_range();
+        let pixels = mapped
+            .chunks_exact(padded_row_bytes as usize)
+            .flat_map(|row| &row[..row_bytes as usize])
+            .copied()
+            .collect();
+        drop(mapped);
+        readback.unmap();
+        RgbaImage::from_raw(frame.width, frame.height, pixels)
+            .ok_or_else(|| RenderError::RenderFailure("Thumbnail readback was short".to_string()))
+    }
+}
+
+/// What one thumbnail is drawn with
+struct Frame {
+    width: u32,
+    height: u32,
+    view_proj: [[f32; 4]; 4],
+    light: LightUniforms,
+    background: [f32; 4],
+}
+
+enum Backend {
+    Gpu(Box<GpuTarget>),
+    Software,
+}
+
+/// Offscreen renderer for document thumbnails
+pub struct Thumbnailer {
+    backend: Backend,
+    settings: RenderSettings,
+    recoveries: u32,
+    /// Clear color
+    pub background: [f32; 4],
+    /// Light the triangles are shaded with
+    pub light: LightUniforms,
+}
+
+impl Thumbnailer {
+    /// Thumbnailer on the adapter the settings pick, or on the CPU
+    ///
+    /// Fails only when no adapter is usable and the settings disallow the
+    /// software fallback.
+    pub fn new(settings: RenderSettings) -> RenderResult<Self> {
+        Ok(Self {
+            backend: Self::open(&settings)?,
+            settings,
+            recoveries: 0,
+            background: [1.0, 1.0, 1.0, 1.0],
+            light: LightUniforms::default(),
+        })
+    }
+
+    fn open(settings: &RenderSettings) -> RenderResult<Backend> {
+        if settings.adapter == AdapterPreference::Software {
+            return Ok(Backend::Software);
+        }
+        let instance = settings.instance();
+        match block_on(select_adapter(&instance, settings, None))? {
+            AdapterSelection::Gpu(adapter) => match GpuTarget::new(&adapter) {
+                Ok(target) => Ok(Backend::Gpu(Box::new(target))),
+                Err(e) if settings.software_fallback => {
+                    log::warn!("Cannot render thumbnails on {}: {}", adapter.get_info().name, e);
+                    Ok(Backend::Software)
+                }
+                Err(e) => Err(e),
+            },
+            AdapterSelection::Software => Ok(Backend::Software),
+        }
+    }
+
+    /// Whether thumbnails are drawn on the CPU
+    pub fn is_software(&self) -> bool {
+        matches!(self.backend, Backend::Software)
+    }
+
+    /// Adapter thumbnails are drawn on, `None` on the CPU
+    pub fn adapter_info(&self) -> Option<&wgpu::AdapterInfo> {
+        match &self.backend {
+            Backend::Gpu(target) => Some(&target.adapter_info),
+            Backend::Software => None,
+        }
+    }
+
+    /// Render triangles and line segments as seen by a camera; the camera's
+    /// aspect ratio is set to the image's
+    pub fn render(
+        &mut self,
+        camera: &mut Camera,
+        triangles: &[MeshVertex],
+        lines: &[LineVertex],
+        width: u32,
+        height: u32,
+    ) -> RenderResult<RgbaImage> {
+        let (width, height) = (width.max(1), height.max(1));
+        camera.set_aspect_ratio(width as f32 / height as f32);
+        let frame = Frame {
+            width,
+            height,
+            view_proj: camera.view_projection_matrix(),
+            light: self.light,
+            background: self.background,
+        };
+
+        loop {
+            let Backend::Gpu(target) = &self.backend else {
+                return Ok(Self::render_software(&frame, triangles, lines));
+            };
+            let error = match target.render(&frame, triangles, lines) {
+                Ok(image) => return Ok(image),
+                Err(e) => e,
+            };
+
+            if self.recoveries < self.settings.max_recoveries {
+                self.recoveries += 1;
+                log::warn!("Thumbnail rendering failed ({}), reopening the device", error);
+                self.backend = Self::open(&self.settings)?;
+            } else if self.settings.software_fallback {
+                log::warn!("Thumbnail rendering failed ({}), switching to the CPU", error);
+                self.backend = Backend::Software;
+            } else {
+                return Err(error);
+            }
+        }
+    }
+
+    fn render_software(frame: &Frame, triangles: &[MeshVertex], lines: &[LineVertex]) -> RgbaImage {
+        let mut raster = SoftwareRasterizer::new(frame.width, frame.height);
+        raster.clear(frame.background);
+        raster.set_view_projection(frame.view_proj);
+        raster.set_light(frame.light);
+        raster.draw_triangles(triangles);
+        raster.draw_lines(lines);
+        raster.into_image()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_software_thumbnail() {
+        let settings = RenderSettings {
+            adapter: AdapterPreference::Software,
+            ..RenderSettings::default()
+        };
+        let mut thumbnailer = Thumbnailer::new(settings).unwrap();
+        assert!(thumbnailer.is_software());
+        assert!(thumbnailer.adapter_info().is_none());
+
+        // A 10 x 10 red square seen from above, filling half the view
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let corners = [[-5.0, -5.0], [5.0, -5.0], [5.0, 5.0], [-5.0, -5.0], [5.0, 5.0], [-5.0, 5.0]];
+        let square = corners.map(|[x, y]| MeshVertex::new([x, y, 0.0], [0.0, 0.0, 1.0], red, [0.0, 0.0]));
+        let mut camera = Camera::new_orthographic(
+            [0.0, 0.0, 100.0].into(),
+            [0.0, 0.0, 0.0].into(),
+            [0.0, 1.0, 0.0].into(),
+            1.0,
+        );
+        camera.set_ortho_height(20.0);
+
+        let image = thumbnailer.render(&mut camera, &square, &[], 64, 32).unwrap();
+        assert_eq!(image.dimensions(), (64, 32));
+        let center = image.get_pixel(32, 16).0;
+        assert!(center[0] > 150 && center[1] == 0 && center[2] == 0, "{:?}", center);
+        assert_eq!(image.get_pixel(2, 16).0, [255, 255, 255, 255]);
+        assert_eq!(image.get_pixel(32, 2).0, [255, 255, 255, 255]);
+    }
+}
@@ -1,6 +1,7 @@
 //! Viewport management for multi-viewport layouts
 
 use super::camera::Camera;
+use super::patterns::PatternView;
 use nalgebra::{Point2, Point3, Vector2};
 
 /// Viewport layout types
@@ -246,6 +247,26 @@ impl Viewport {
             }
         }
     }
+
+    /// Drawing region and zoom that hatch and linetype strokes are
+    /// generated for; changes whenever the view is panned or zoomed
+    ///
+    /// Exact for plan views; other views get the region around the camera
+    /// target at the target's depth.
+    pub fn pattern_view(&self) -> PatternView {
+        let pixel_size = self.pixel_size_at_depth(self.camera.distance()) as f64;
+        let target = self.camera.target();
+        let half = [
+            pixel_size * self.width as f64 / 2.0,
+            pixel_size * self.height as f64 / 2.0,
+        ];
+        let center = [target.x as f64, target.y as f64];
+        PatternView::new(
+            [center[0] - half[0], center[1] - half[1]],
+            [center[0] + half[0], center[1] + half[1]],
+            pixel_size,
+        )
+    }
 }
 
 /// Viewport manager for handling multiple viewports
@@ -430,4 +451,21 @@ mod tests {
         assert!((vp.x - 0.5).abs() < 0.01);
         assert!((vp.y - 0.5).abs() < 0.01);
     }
+
+    #[test]
+    fn test_pattern_view_follows_zoom() {
+        let camera = Camera::new_orthographic(
+            Point3::new(10.0, 20.0, 100.0),
+            Point3::new(10.0, 20.0, 0.0),
+            nalgebra::Vector3::new(0.0, 1.0, 0.0),
+            2.0,
+        );
+
+        let mut viewport = Viewport::new(camera, 0, 0, 800, 400);
+        let view = viewport.pattern_view();
+        assert_eq!(view, PatternView::new([-90.0, -30.0], [110.0, 70.0], 0.25));
+
+        viewport.camera_mut().set_ortho_height(50.0);
+        assert_eq!(viewport.pattern_view().pixel_size, 0.125);
+    }
 }
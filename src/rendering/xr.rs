@@ -0,0 +1,497 @@
+//! Headset review through an OpenXR-style session
+//!
+//! [`XrSession`] is what the renderer needs from an XR runtime each frame:
+//! the tracked pose and field of view of both eyes, an image per eye to
+//! draw into, and the state of the hand controllers. It follows the
+//! OpenXR frame loop (wait, acquire, end); the binding owns the instance,
+//! session and swapchains, and its images must live on the renderer's
+//! device. With the `openxr` feature, `OpenXrSession`
+//! is that binding.
+//!
+//! Poses are in the runtime's stage space: meters, Y up, the floor at
+//! Y = 0, eyes and controllers looking down -Z. [`XrNavigator`] places the
+//! model in the room and moves it with the controllers; [`XrMeasure`]
+//! measures between points picked with a controller ray.
+
+use super::stereo::{frustum, Eye};
+use super::RenderResult;
+use nalgebra::{Matrix4, Point3, Rotation3, Translation3, UnitQuaternion, Vector3};
+use serde::{Deserialize, Serialize};
+
+/// Near clip distance of headset views, in meters
+pub const XR_NEAR: f32 = 0.05;
+
+/// Far clip distance of headset views, in meters
+pub const XR_FAR: f32 = 200.0;
+
+/// Thumbstick deflection that triggers a snap turn
+const SNAP_THRESHOLD: f32 = 0.7;
+
+/// Thumbstick deflection below which the next snap turn is armed
+const SNAP_RELEASE: f32 = 0.3;
+
+/// Grip or trigger value that counts as pressed
+const PRESSED: f32 = 0.5;
+
+/// Position and orientation in stage space
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct XrPose {
+    pub position: Point3<f32>,
+    pub orientation: UnitQuaternion<f32>,
+}
+
+impl XrPose {
+    /// Pose at `position` looking down -Z
+    pub fn at(position: Point3<f32>) -> Self {
+        Self {
+            position,
+            orientation: UnitQuaternion::identity(),
+        }
+    }
+
+    /// Direction the pose looks in
+    pub fn forward(&self) -> Vector3<f32> {
+        self.orientation * -Vector3::z()
+    }
+
+    /// Stage space from pose space
+    pub fn matrix(&self) -> Matrix4<f32> {
+        Translation3::from(self.position.coords).to_homogeneous() * self.orientation.to_homogeneous()
+    }
+
+    /// Pose space from stage space, the view matrix of an eye
+    pub fn view_matrix(&self) -> Matrix4<f32> {
+        self.orientation.inverse().to_homogeneous()
+            * Translation3::from(-self.position.coords).to_homogeneous()
+    }
+}
+
+/// Field of view of an eye as angles from its forward direction; left and
+/// down are negative
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct XrFov {
+    pub angle_left: f32,
+    pub angle_right: f32,
+    pub angle_up: f32,
+    pub angle_down: f32,
+}
+
+impl XrFov {
+    /// Symmetric field of view
+    pub fn symmetric(horizontal: f32, vertical: f32) -> Self {
+        Self {
+            angle_left: -horizontal / 2.0,
+            angle_right: horizontal / 2.0,
+            angle_up: vertical / 2.0,
+            angle_down: -vertical / 2.0,
+        }
+    }
+
+    /// Projection with depth in 0..1
+    pub fn projection(&self, near: f32, far: f32) -> Matrix4<f32> {
+        frustum(
+            near * self.angle_left.tan(),
+            near * self.angle_right.tan(),
+            near * self.angle_down.tan(),
+            near * self.angle_up.tan(),
+            near,
+            far,
+        )
+    }
+}
+
+/// Tracked view of one eye
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct XrView {
+    pub pose: XrPose,
+    pub fov: XrFov,
+}
+
+/// A frame the runtime wants rendered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XrFrame {
+    /// When the frame will be shown, in the runtime's nanoseconds
+    pub display_time: i64,
+    /// Left and right eye views
+    pub views: [XrView; 2],
+    /// Size of each eye image in pixels
+    pub size: (u32, u32),
+}
+
+impl XrFrame {
+    /// View of one eye
+    pub fn view(&self, eye: Eye) -> &XrView {
+        match eye {
+            Eye::Left => &self.views[0],
+            Eye::Right => &self.views[1],
+        }
+    }
+
+    /// Point between the eyes
+    pub fn head(&self) -> Point3<f32> {
+        Point3::from((self.views[0].pose.position.coords + self.views[1].pose.position.coords) / 2.0)
+    }
+}
+
+/// Hand holding a controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+/// Controller input for one frame
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ControllerState {
+    /// Pose the controller points with
+    pub aim: XrPose,
+    /// Thumbstick, each axis in -1..1 with +Y pushed forward
+    pub thumbstick: [f32; 2],
+    /// Trigger pull in 0..1
+    pub trigger: f32,
+    /// Grip squeeze in 0..1
+    pub grip: f32,
+}
+
+/// Connection to an XR runtime
+pub trait XrSession {
+    /// Format of the swapchain images; must be the renderer's surface format
+    fn format(&self) -> wgpu::TextureFormat;
+
+    /// Wait for the next frame; `None` when the runtime does not want one
+    /// rendered, such as while the headset is off
+    fn wait_frame(&mut self) -> RenderResult<Option<XrFrame>>;
+
+    /// Image to draw one eye of the current frame into
+    fn acquire_image(&mut self, eye: Eye) -> RenderResult<wgpu::TextureView>;
+
+    /// Hand the rendered frame to the compositor
+    fn end_frame(&mut self, frame: &XrFrame) -> RenderResult<()>;
+
+    /// Controller in a hand, `None` when it is not tracked
+    fn controller(&self, hand: Hand) -> Option<ControllerState>;
+}
+
+/// Controller grab in progress
+#[derive(Debug, Clone, Copy)]
+enum Grab {
+    One { hand: Hand, last: Point3<f32> },
+    Both { left: Point3<f32>, right: Point3<f32> },
+}
+
+/// Where the model sits in the room, moved with the controllers
+///
+/// - Left thumbstick flies along the left controller's aim
+/// - Right thumbstick left or right snap-turns about the head
+/// - Gripping with one hand drags the model; with both, moves and scales it
+#[derive(Debug, Clone)]
+pub struct XrNavigator {
+    /// Meters per model unit
+    pub scale: f32,
+    /// Turn of the model about the vertical, in radians
+    pub yaw: f32,
+    /// Stage position of the model origin
+    pub offset: Vector3<f32>,
+    /// Model Z is up, as in drawings; otherwise model Y is
+    pub z_up: bool,
+    /// Flying speed in meters per second at full deflection
+    pub fly_speed: f32,
+    /// Snap turn step in radians
+    pub snap_angle: f32,
+    snap_armed: bool,
+    grab: Option<Grab>,
+}
+
+impl XrNavigator {
+    /// Model at `scale` meters per unit, its origin on a table a meter in
+    /// front of the stage origin
+    pub fn new(scale: f32) -> Self {
+        Self {
+            scale,
+            yaw: 0.0,
+            offset: Vector3::new(0.0, 1.0, -1.0),
+            z_up: true,
+            fly_speed: 1.5,
+            snap_angle: 30f32.to_radians(),
+            snap_armed: true,
+            grab: None,
+        }
+    }
+
+    /// Stage space from model space
+    pub fn stage_from_model(&self) -> Matrix4<f32> {
+        let up = if self.z_up {
+            Rotation3::from_axis_angle(&Vector3::x_axis(), -std::f32::consts::FRAC_PI_2)
+        } else {
+            Rotation3::identity()
+        };
+        Translation3::from(self.offset).to_homogeneous()
+            * Rotation3::from_axis_angle(&Vector3::y_axis(), self.yaw).to_homogeneous()
+            * Matrix4::new_scaling(self.scale)
+            * up.to_homogeneous()
+    }
+
+    /// Model space from stage space
+    pub fn model_from_stage(&self) -> Matrix4<f32> {
+        self.stage_from_model().try_inverse().unwrap_or_else(Matrix4::identity)
+    }
+
+    /// View-projection of an eye onto the model, column-major as in
+    /// [`super::TransformUniforms`]
+    pub fn eye_view_projection(&self, view: &XrView) -> [[f32; 4]; 4] {
+        (view.fov.projection(XR_NEAR, XR_FAR) * view.pose.view_matrix() * self.stage_from_model()).into()
+    }
+
+    /// Controller ray in model space, as origin and unit direction
+    pub fn ray(&self, aim: &XrPose) -> (Point3<f32>, Vector3<f32>) {
+        let m = self.model_from_stage();
+        let origin = m.transform_point(&aim.position);
+        let direction = m.transform_vector(&aim.forward()).normalize();
+        (origin, direction)
+    }
+
+    /// Apply a frame of controller input, `dt` seconds after the last
+    pub fn update(
+        &mut self,
+        head: Point3<f32>,
+        left: Option<&ControllerState>,
+        right: Option<&ControllerState>,
+        dt: f32,
+    ) {
+        let gripping = |c: Option<&ControllerState>| c.filter(|c| c.grip > PRESSED).map(|c| c.aim.position);
+        match (gripping(left), gripping(right)) {
+            (Some(l), Some(r)) => {
+                if let Some(Grab::Both { left: last_l, right: last_r }) = self.grab {
+                    let (last_mid, mid) = (midpoint(last_l, last_r), midpoint(l, r));
+                    let (last_span, span) = ((last_r - last_l).norm(), (r - l).norm());
+                    if last_span > f32::EPSILON && span > f32::EPSILON {
+                        let ratio = span / last_span;
+                        self.offset = mid.coords + (self.offset - last_mid.coords) * ratio;
+                        self.scale *= ratio;
+                    }
+                }
+                self.grab = Some(Grab::Both { left: l, right: r });
+            }
+            (Some(p), None) | (None, Some(p)) => {
+                let hand = if gripping(left).is_some() { Hand::Left } else { Hand::Right };
+                if let Some(Grab::One { hand: last_hand, last }) = self.grab {
+                    if last_hand == hand {
+                        self.offset += p - last;
+                    }
+                }
+                self.grab = Some(Grab::One { hand, last: p });
+            }
+            (None, None) => self.grab = None,
+        }
+
+        if let Some(left) = left {
+            let [x, y] = left.thumbstick;
+            let forward = flatten(left.aim.forward());
+            let side = flatten(left.aim.orientation * Vector3::x());
+            // Flying forward moves the model back towards the viewer
+            self.offset -= (forward * y + side * x) * self.fly_speed * dt;
+        }
+
+        if let Some(right) = right {
+            let x = right.thumbstick[0];
+            if self.snap_armed && x.abs() > SNAP_THRESHOLD {
+                // Turning right swings the model to the left about the head
+                let turn = self.snap_angle * x.signum();
+                let rotation = Rotation3::from_axis_angle(&Vector3::y_axis(), turn);
+                let head = Vector3::new(head.x, 0.0, head.z);
+                self.offset = head + rotation * (self.offset - head);
+                self.yaw += turn;
+                self.snap_armed = false;
+            } else if x.abs() < SNAP_RELEASE {
+                self.snap_armed = true;
+            }
+        }
+    }
+}
+
+impl Default for XrNavigator {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+/// Point-to-point distance measured with a controller
+///
+/// Each trigger press picks the point the controller ray hits; the second
+/// completes a measurement and the third starts a new one.
+#[derive(Debug, Clone, Default)]
+pub struct XrMeasure {
+    points: Vec<Point3<f32>>,
+    pressed: bool,
+}
+
+impl XrMeasure {
+    /// Measurement with nothing picked
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a frame of input from the measuring controller; `pick` finds
+    /// where a model-space ray hits the model. Returns the distance, in
+    /// model units, when a measurement completes.
+    pub fn update<F>(&mut self, controller: &ControllerState, navigator: &XrNavigator, pick: F) -> Option<f32>
+    where
+        F: FnOnce(Point3<f32>, Vector3<f32>) -> Option<Point3<f32>>,
+    {
+        let pressed = controller.trigger > PRESSED;
+        let clicked = pressed && !self.pressed;
+        self.pressed = pressed;
+        if !clicked {
+            return None;
+        }
+        let (origin, direction) = navigator.ray(&controller.aim);
+        let point = pick(origin, direction)?;
+        if self.points.len() == 2 {
+            self.points.clear();
+        }
+        self.points.push(point);
+        self.distance()
+    }
+
+    /// Picked points, in model space
+    pub fn points(&self) -> &[Point3<f32>] {
+        &self.points
+    }
+
+    /// Distance between the two picked points
+    pub fn distance(&self) -> Option<f32> {
+        match self.points[..] {
+            [a, b] => Some((b - a).norm()),
+            _ => None,
+        }
+    }
+
+    /// Forget the picked points
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+}
+
+/// Nearest point where a ray hits a triangle
+pub fn pick_triangles(
+    origin: Point3<f32>,
+    direction: Vector3<f32>,
+    triangles: &[[Point3<f32>; 3]],
+) -> Option<Point3<f32>> {
+    triangles
+        .iter()
+        .filter_map(|[a, b, c]| {
+            // Möller-Trumbore
+            let (e1, e2) = (b - a, c - a);
+            let p = direction.cross(&e2);
+            let det = e1.dot(&p);
+            if det.abs() < f32::EPSILON {
+                return None;
+            }
+            let s = origin - a;
+            let u = s.dot(&p) / det;
+            let q = s.cross(&e1);
+            let v = direction.dot(&q) / det;
+            let t = e2.dot(&q) / det;
+            (u >= 0.0 && v >= 0.0 && u + v <= 1.0 && t > 0.0).then_some(t)
+        })
+        .min_by(f32::total_cmp)
+        .map(|t| origin + direction * t)
+}
+
+fn midpoint(a: Point3<f32>, b: Point3<f32>) -> Point3<f32> {
+    Point3::from((a.coords + b.coords) / 2.0)
+}
+
+/// Horizontal part of a direction, unit length or zero
+fn flatten(v: Vector3<f32>) -> Vector3<f32> {
+    let v = Vector3::new(v.x, 0.0, v.z);
+    v.try_normalize(f32::EPSILON).unwrap_or_else(Vector3::zeros)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector4;
+
+    fn controller(position: Point3<f32>) -> ControllerState {
+        ControllerState {
+            aim: XrPose::at(position),
+            thumbstick: [0.0, 0.0],
+            trigger: 0.0,
+            grip: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_navigation_and_measurement() {
+        // A part in millimetres, shown at full size
+        let mut nav = XrNavigator::new(0.001);
+        let head = Point3::new(0.0, 1.6, 0.0);
+
+        // Model +Z maps to stage +Y; the origin sits on the table
+        let up = nav.stage_from_model().transform_point(&Point3::new(0.0, 0.0, 100.0));
+        assert!((up - Point3::new(0.0, 1.1, -1.0)).norm() < 1e-5);
+
+        // An eye at the head looking forward sees the model origin ahead
+        let view = XrView {
+            pose: XrPose::at(head),
+            fov: XrFov::symmetric(90f32.to_radians(), 90f32.to_radians()),
+        };
+        let clip = Matrix4::from(nav.eye_view_projection(&view)) * Vector4::new(0.0, 0.0, 0.0, 1.0);
+        let ndc = clip.xyz() / clip.w;
+        assert!(ndc.x.abs() < 1e-5 && ndc.y < 0.0 && (0.0..1.0).contains(&ndc.z));
+
+        // Flying forward at full stick for a second brings the model closer
+        let mut left = controller(Point3::new(-0.2, 1.0, -0.3));
+        left.thumbstick = [0.0, 1.0];
+        nav.update(head, Some(&left), None, 1.0);
+        assert!((nav.offset.z - (-1.0 + nav.fly_speed)).abs() < 1e-5);
+        left.thumbstick = [0.0, 0.0];
+
+        // One snap turn per push of the stick
+        let mut right = controller(Point3::new(0.2, 1.0, -0.3));
+        right.thumbstick = [1.0, 0.0];
+        nav.update(head, Some(&left), Some(&right), 0.0);
+        nav.update(head, Some(&left), Some(&right), 0.0);
+        assert!((nav.yaw - nav.snap_angle).abs() < 1e-6);
+        right.thumbstick = [0.0, 0.0];
+        nav.update(head, Some(&left), Some(&right), 0.0);
+        nav.yaw = 0.0;
+        nav.offset = Vector3::new(0.0, 1.0, -1.0);
+
+        // Pulling the hands apart to twice the span doubles the scale
+        left.grip = 1.0;
+        right.grip = 1.0;
+        nav.update(head, Some(&left), Some(&right), 0.0);
+        left.aim.position.x = -0.4;
+        right.aim.position.x = 0.4;
+        nav.update(head, Some(&left), Some(&right), 0.0);
+        assert!((nav.scale - 0.002).abs() < 1e-7);
+        left.grip = 0.0;
+        right.grip = 0.0;
+        nav.update(head, Some(&left), Some(&right), 0.0);
+
+        // Measure across the top of a 100 x 100 slab 20 high, pointing
+        // straight down at two corners
+        let slab = [
+            [Point3::new(0.0, 0.0, 20.0), Point3::new(100.0, 0.0, 20.0), Point3::new(100.0, 100.0, 20.0)],
+            [Point3::new(0.0, 0.0, 20.0), Point3::new(100.0, 100.0, 20.0), Point3::new(0.0, 100.0, 20.0)],
+            [Point3::new(0.0, 0.0, 0.0), Point3::new(100.0, 0.0, 0.0), Point3::new(100.0, 100.0, 0.0)],
+        ];
+        let down = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), -std::f32::consts::FRAC_PI_2);
+        let mut measure = XrMeasure::new();
+        let mut pointer = controller(Point3::origin());
+        pointer.aim.orientation = down;
+        for (x, y) in [(10.0, 10.0), (10.0, 90.0)] {
+            let stage = nav.stage_from_model().transform_point(&Point3::new(x, y, 200.0));
+            pointer.aim.position = stage;
+            pointer.trigger = 0.0;
+            assert_eq!(measure.update(&pointer, &nav, |o, d| pick_triangles(o, d, &slab)), None);
+            pointer.trigger = 1.0;
+            measure.update(&pointer, &nav, |o, d| pick_triangles(o, d, &slab));
+        }
+        assert!((measure.distance().unwrap() - 80.0).abs() < 1e-3);
+        assert!(measure.points().iter().all(|p| (p.z - 20.0).abs() < 1e-3));
+    }
+}
@@ -0,0 +1,680 @@
+//! OpenXR binding for [`XrSession`]
+//!
+//! [`OpenXrSession::new`] starts OpenXR on the headset and has the runtime
+//! create the Vulkan instance and device, which are wrapped for wgpu so the
+//! renderer and the runtime's swapchain share them. Hand the returned
+//! [`OpenedDevice`] to [`Renderer::with_device`], then call
+//! [`OpenXrSession::create_swapchain`] with the renderer's surface format.
+//!
+//! Both eyes draw into one two-layer swapchain. Controllers are bound
+//! through the Oculus Touch interaction profile, which runtimes remap to
+//! their own controllers. Drop the session before the renderer; the
+//! runtime's objects refer to the device.
+//!
+//! [`Renderer::with_device`]: super::Renderer::with_device
+
+use super::adapter::{device_descriptor, OpenedDevice};
+use super::stereo::Eye;
+use super::xr::{ControllerState, Hand, XrFov, XrFrame, XrPose, XrSession, XrView};
+use super::{RenderError, RenderResult};
+use ash::vk::{self, Handle};
+use nalgebra::{Point3, Quaternion, UnitQuaternion};
+use openxr as xr;
+use std::ffi::CString;
+use std::fmt::Display;
+use std::sync::Arc;
+use wgpu::hal::{self, api::Vulkan};
+
+/// Vulkan version the instance and device are created for
+const VK_VERSION: u32 = vk::make_api_version(0, 1, 1, 0);
+
+/// Headsets show one view per eye
+const VIEW_TYPE: xr::ViewConfigurationType = xr::ViewConfigurationType::PRIMARY_STEREO;
+
+/// Surface formats the renderer may pick and their Vulkan equivalents
+const FORMATS: [(wgpu::TextureFormat, vk::Format); 4] = [
+    (wgpu::TextureFormat::Bgra8UnormSrgb, vk::Format::B8G8R8A8_SRGB),
+    (wgpu::TextureFormat::Rgba8UnormSrgb, vk::Format::R8G8B8A8_SRGB),
+    (wgpu::TextureFormat::Bgra8Unorm, vk::Format::B8G8R8A8_UNORM),
+    (wgpu::TextureFormat::Rgba8Unorm, vk::Format::R8G8B8A8_UNORM),
+];
+
+/// Controller actions, each bound for both hands
+struct Controls {
+    set: xr::ActionSet,
+    thumbstick: xr::Action<xr::Vector2f>,
+    trigger: xr::Action<f32>,
+    grip: xr::Action<f32>,
+    /// Left and right hand subaction paths
+    hands: [xr::Path; 2],
+    /// Left and right aim poses
+    spaces: [xr::Space; 2],
+}
+
+impl Controls {
+    fn new(instance: &xr::Instance, session: &xr::Session<xr::Vulkan>) -> xr::Result<Self> {
+        let hands = [
+            instance.string_to_path("/user/hand/left")?,
+            instance.string_to_path("/user/hand/right")?,
+        ];
+        let set = instance.create_action_set("review", "Model review", 0)?;
+        let aim = set.create_action::<xr::Posef>("aim", "Aim", &hands)?;
+        let thumbstick = set.create_action::<xr::Vector2f>("thumbstick", "Thumbstick", &hands)?;
+        let trigger = set.create_action::<f32>("trigger", "Trigger", &hands)?;
+        let grip = set.create_action::<f32>("grip", "Grip", &hands)?;
+
+        let mut bindings = Vec::new();
+        for side in ["left", "right"] {
+            let path = |input: &str| instance.string_to_path(&format!("/user/hand/{}/input/{}", side, input));
+            bindings.push(xr::Binding::new(&aim, path("aim/pose")?));
+            bindings.push(xr::Binding::new(&thumbstick, path("thumbstick")?));
+            bindings.push(xr::Binding::new(&trigger, path("trigger/value")?));
+            bindings.push(xr::Binding::new(&grip, path("squeeze/value")?));
+        }
+        let profile = instance.string_to_path("/interaction_profiles/oculus/touch_controller")?;
+        instance.suggest_interaction_profile_bindings(profile, &bindings)?;
+        session.attach_action_sets(&[&set])?;
+
+        let spaces = [
+            aim.create_space(session.clone(), hands[0], xr::Posef::IDENTITY)?,
+            aim.create_space(session.clone(), hands[1], xr::Posef::IDENTITY)?,
+        ];
+        Ok(Self { set, thumbstick, trigger, grip, hands, spaces })
+    }
+
+    /// Both controllers at `time`, after the actions have been synced
+    fn read(
+        &self,
+        session: &xr::Session<xr::Vulkan>,
+        stage: &xr::Space,
+        time: xr::Time,
+    ) -> xr::Result<[Option<ControllerState>; 2]> {
+        let mut states = [None, None];
+        for (i, &hand) in self.hands.iter().enumerate() {
+            let location = self.spaces[i].locate(stage, time)?;
+            let tracked = xr::SpaceLocationFlags::POSITION_VALID | xr::SpaceLocationFlags::ORIENTATION_VALID;
+            if !location.location_flags.contains(tracked) {
+                continue;
+            }
+            let stick = self.thumbstick.state(session, hand)?.current_state;
+            states[i] = Some(ControllerState {
+                aim: pose(location.pose),
+                thumbstick: [stick.x, stick.y],
+                trigger: self.trigger.state(session, hand)?.current_state,
+                grip: self.grip.state(session, hand)?.current_state,
+            });
+        }
+        Ok(states)
+    }
+}
+
+/// Eye images and the wgpu textures over them
+struct Swapchain {
+    /// Declared before `handle` so the textures go before the images do
+    textures: Vec<wgpu::Texture>,
+    handle: xr::Swapchain<xr::Vulkan>,
+    format: wgpu::TextureFormat,
+    size: (u32, u32),
+    /// Image acquired for the frame being rendered
+    acquired: Option<usize>,
+}
+
+/// Frame between `wait_frame` and `end_frame`
+struct PendingFrame {
+    time: xr::Time,
+    views: Vec<xr::View>,
+}
+
+/// [`XrSession`] over an OpenXR runtime on Vulkan
+pub struct OpenXrSession {
+    swapchain: Option<Swapchain>,
+    pending: Option<PendingFrame>,
+    controllers: [Option<ControllerState>; 2],
+    controls: Controls,
+    stage: xr::Space,
+    stream: xr::FrameStream<xr::Vulkan>,
+    waiter: xr::FrameWaiter,
+    session: xr::Session<xr::Vulkan>,
+    instance: xr::Instance,
+    system: xr::SystemId,
+    blend_mode: xr::EnvironmentBlendMode,
+    device: Arc<wgpu::Device>,
+    /// Swapchain formats the runtime supports, as raw Vulkan formats
+    formats: Vec<u32>,
+    events: xr::EventDataBuffer,
+    running: bool,
+    exiting: bool,
+}
+
+impl OpenXrSession {
+    /// Start OpenXR on the headset and open the Vulkan device it renders on
+    pub fn new(app_name: &str) -> RenderResult<(Self, OpenedDevice)> {
+        // SAFETY: loads the system's OpenXR loader, which has no other
+        // preconditions
+        let entry = unsafe { xr::Entry::load() }.map_err(init_error("loading the runtime"))?;
+        let available = entry.enumerate_extensions().map_err(init_error("listing extensions"))?;
+        if !available.khr_vulkan_enable2 {
+            return Err(RenderError::InitializationError(
+                "the OpenXR runtime does not support Vulkan".to_string(),
+            ));
+        }
+        let mut extensions = xr::ExtensionSet::default();
+        extensions.khr_vulkan_enable2 = true;
+        let app = xr::ApplicationInfo {
+            application_name: app_name,
+            application_version: 0,
+            engine_name: "CADDY",
+            engine_version: 0,
+        };
+        let instance = entry
+            .create_instance(&app, &extensions, &[])
+            .map_err(init_error("creating the instance"))?;
+        let system = instance
+            .system(xr::FormFactor::HEAD_MOUNTED_DISPLAY)
+            .map_err(init_error("finding a headset"))?;
+        let blend_mode = instance
+            .enumerate_environment_blend_modes(system, VIEW_TYPE)
+            .map_err(init_error("listing blend modes"))?
+            .first()
+            .copied()
+            .unwrap_or(xr::EnvironmentBlendMode::OPAQUE);
+        let requirements = instance
+            .graphics_requirements::<xr::Vulkan>(system)
+            .map_err(init_error("reading the Vulkan requirements"))?;
+        let min = requirements.min_api_version_supported;
+        if (min.major(), min.minor()) > (1, 1) {
+            return Err(RenderError::InitializationError(format!(
+                "the OpenXR runtime needs Vulkan {}.{}",
+                min.major(),
+                min.minor()
+            )));
+        }
+
+        // The runtime creates the Vulkan instance with the extensions wgpu
+        // wants plus its own
+        // SAFETY: loads the system's Vulkan loader
+        let vk_entry = unsafe { ash::Entry::load() }.map_err(init_error("loading Vulkan"))?;
+        let flags = wgpu::InstanceFlags::empty();
+        let instance_extensions = hal::vulkan::Instance::desired_extensions(&vk_entry, VK_VERSION, flags)
+            .map_err(init_error("listing Vulkan extensions"))?;
+        let app_name = CString::new(app_name).map_err(init_error("naming the application"))?;
+        let app_info = vk::ApplicationInfo::builder()
+            .application_name(&app_name)
+            .api_version(VK_VERSION);
+        let names: Vec<_> = instance_extensions.iter().map(|name| name.as_ptr()).collect();
+        let create_info = vk::InstanceCreateInfo::builder()
+            .application_info(&app_info)
+            .enabled_extension_names(&names);
+        // SAFETY: both types are the C signature of vkGetInstanceProcAddr
+        let get_instance_proc_addr: xr::sys::platform::VkGetInstanceProcAddr =
+            unsafe { std::mem::transmute(vk_entry.static_fn().get_instance_proc_addr) };
+        // SAFETY: the create info and everything it points to outlive the
+        // call, and a handle is only loaded when the runtime reports success
+        let vk_instance = unsafe {
+            let raw = instance
+                .create_vulkan_instance(
+                    system,
+                    get_instance_proc_addr,
+                    &*create_info as *const vk::InstanceCreateInfo as *const _,
+                )
+                .map_err(init_error("creating the Vulkan instance"))?
+                .map_err(|code| init_error("creating the Vulkan instance")(vk::Result::from_raw(code as _)))?;
+            ash::Instance::load(vk_entry.static_fn(), vk::Instance::from_raw(raw as _))
+        };
+        // SAFETY: the instance was created by the runtime for this system
+        let physical = unsafe { instance.vulkan_graphics_device(system, vk_instance.handle().as_raw() as _) }
+            .map_err(init_error("finding the headset's GPU"))?;
+        let physical = vk::PhysicalDevice::from_raw(physical as _);
+
+        // SAFETY: wgpu takes ownership of an instance created with the
+        // extensions it asked for
+        let hal_instance = unsafe {
+            hal::vulkan::Instance::from_raw(
+                vk_entry.clone(),
+                vk_instance.clone(),
+                VK_VERSION,
+                0,
+                None,
+                instance_extensions,
+                flags,
+                false,
+                None,
+            )
+        }
+        .map_err(init_error("wrapping the Vulkan instance"))?;
+        let exposed = hal_instance.expose_adapter(physical).ok_or_else(|| {
+            RenderError::InitializationError("wgpu cannot use the headset's GPU".to_string())
+        })?;
+        let descriptor = device_descriptor(exposed.features);
+        let features = descriptor.required_features;
+
+        // SAFETY: the physical device belongs to the instance
+        let families = unsafe { vk_instance.get_physical_device_queue_family_properties(physical) };
+        let family = families
+            .iter()
+            .position(|family| family.queue_flags.contains(vk::QueueFlags::GRAPHICS))
+            .ok_or_else(|| {
+                RenderError::InitializationError("the headset's GPU has no graphics queue".to_string())
+            })? as u32;
+        let device_extensions = exposed.adapter.required_device_extensions(features);
+        let mut enabled = exposed.adapter.physical_device_features(&device_extensions, features);
+        let priorities = [1.0];
+        let queues = [vk::DeviceQueueCreateInfo::builder()
+            .queue_family_index(family)
+            .queue_priorities(&priorities)
+            .build()];
+        let names: Vec<_> = device_extensions.iter().map(|name| name.as_ptr()).collect();
+        let create_info = enabled
+            .add_to_device_create_builder(
+                vk::DeviceCreateInfo::builder()
+                    .queue_create_infos(&queues)
+                    .enabled_extension_names(&names),
+            )
+            .build();
+        // SAFETY: as for the instance; wgpu takes ownership of the device
+        let vk_device = unsafe {
+            let raw = instance
+                .create_vulkan_device(
+                    system,
+                    get_instance_proc_addr,
+                    physical.as_raw() as _,
+                    &create_info as *const vk::DeviceCreateInfo as *const _,
+                )
+                .map_err(init_error("creating the Vulkan device"))?
+                .map_err(|code| init_error("creating the Vulkan device")(vk::Result::from_raw(code as _)))?;
+            ash::Device::load(vk_instance.fp_v1_0(), vk::Device::from_raw(raw as _))
+        };
+        let device_handle = vk_device.handle();
+        // SAFETY: the device was created on this adapter with these
+        // extensions and features, and has a queue in `family`
+        let open = unsafe {
+            exposed
+                .adapter
+                .device_from_raw(vk_device, true, &device_extensions, features, family, 0)
+        }
+        .map_err(init_error("wrapping the Vulkan device"))?;
+        // SAFETY: the hal objects were created above and are handed over whole
+        let (wgpu_instance, adapter, (device, queue)) = unsafe {
+            let wgpu_instance = wgpu::Instance::from_hal::<Vulkan>(hal_instance);
+            let adapter = wgpu_instance.create_adapter_from_hal(exposed);
+            let device = adapter
+                .create_device_from_hal(open, &descriptor, None)
+                .map_err(init_error("opening the device"))?;
+            (wgpu_instance, adapter, device)
+        };
+        let device = Arc::new(device);
+
+        let session_info = xr::vulkan::SessionCreateInfo {
+            instance: vk_instance.handle().as_raw() as _,
+            physical_device: physical.as_raw() as _,
+            device: device_handle.as_raw() as _,
+            queue_family_index: family,
+            queue_index: 0,
+        };
+        // SAFETY: the handles are the ones the runtime created for this system
+        let (session, waiter, stream) =
+            unsafe { instance.create_session::<xr::Vulkan>(system, &session_info) }
+                .map_err(init_error("creating the session"))?;
+        let stage = session
+            .create_reference_space(xr::ReferenceSpaceType::STAGE, xr::Posef::IDENTITY)
+            .map_err(init_error("creating the stage space"))?;
+        let controls = Controls::new(&instance, &session).map_err(init_error("binding the controllers"))?;
+        let formats = session
+            .enumerate_swapchain_formats()
+            .map_err(init_error("listing swapchain formats"))?;
+
+        let opened = OpenedDevice {
+            instance: wgpu_instance,
+            adapter,
+            device: device.clone(),
+            queue: Arc::new(queue),
+        };
+        let session = Self {
+            swapchain: None,
+            pending: None,
+            controllers: [None, None],
+            controls,
+            stage,
+            stream,
+            waiter,
+            session,
+            instance,
+            system,
+            blend_mode,
+            device,
+            formats,
+            events: xr::EventDataBuffer::new(),
+            running: false,
+            exiting: false,
+        };
+        Ok((session, opened))
+    }
+
+    /// Create the eye images in `format`, which must be the renderer's
+    /// surface format
+    pub fn create_swapchain(&mut self, format: wgpu::TextureFormat) -> RenderResult<()> {
+        let vk_format = FORMATS
+            .iter()
+            .find(|(f, vk)| *f == format && self.formats.contains(&(vk.as_raw() as u32)))
+            .map(|(_, vk)| *vk)
+            .ok_or_else(|| {
+                RenderError::InitializationError(format!("the OpenXR runtime cannot show {:?}", format))
+            })?;
+        let views = self
+            .instance
+            .enumerate_view_configuration_views(self.system, VIEW_TYPE)
+            .map_err(init_error("listing views"))?;
+        let view = views
+            .first()
+            .ok_or_else(|| RenderError::InitializationError("the headset reports no views".to_string()))?;
+        let size = (view.recommended_image_rect_width, view.recommended_image_rect_height);
+
+        // Drop the old swapchain before the runtime hands out a new one
+        self.swapchain = None;
+        let handle = self
+            .session
+            .create_swapchain(&xr::SwapchainCreateInfo {
+                create_flags: xr::SwapchainCreateFlags::EMPTY,
+                usage_flags: xr::SwapchainUsageFlags::COLOR_ATTACHMENT,
+                format: vk_format.as_raw() as u32,
+                sample_count: 1,
+                width: size.0,
+                height: size.1,
+                face_count: 1,
+                array_size: 2,
+                mip_count: 1,
+            })
+            .map_err(init_error("creating the swapchain"))?;
+        let images = handle.enumerate_images().map_err(init_error("listing swapchain images"))?;
+
+        let extent = wgpu::Extent3d {
+            width: size.0,
+            height: size.1,
+            depth_or_array_layers: 2,
+        };
+        let textures = images
+            .into_iter()
+            .map(|image| {
+                let hal_desc = hal::TextureDescriptor {
+                    label: Some("XR Swapchain"),
+                    size: extent,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: hal::TextureUses::COLOR_TARGET,
+                    memory_flags: hal::MemoryFlags::empty(),
+                    view_formats: Vec::new(),
+                };
+                let desc = wgpu::TextureDescriptor {
+                    label: Some("XR Swapchain"),
+                    size: extent,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                };
+                // SAFETY: the runtime owns the image and keeps it for the
+                // life of the swapchain, which outlives the texture; the
+                // drop guard stops wgpu destroying it
+                unsafe {
+                    let raw = hal::vulkan::Device::texture_from_raw(
+                        vk::Image::from_raw(image),
+                        &hal_desc,
+                        Some(Box::new(())),
+                    );
+                    self.device.create_texture_from_hal::<Vulkan>(raw, &desc)
+                }
+            })
+            .collect();
+
+        self.swapchain = Some(Swapchain {
+            textures,
+            handle,
+            format,
+            size,
+            acquired: None,
+        });
+        Ok(())
+    }
+
+    /// The runtime is shutting the session down; stop calling `wait_frame`
+    pub fn is_exiting(&self) -> bool {
+        self.exiting
+    }
+
+    /// Begin or end the session as the runtime asks
+    fn poll_events(&mut self) -> RenderResult<()> {
+        while let Some(event) = self
+            .instance
+            .poll_event(&mut self.events)
+            .map_err(frame_error("polling events"))?
+        {
+            match event {
+                xr::Event::SessionStateChanged(change) => match change.state() {
+                    xr::SessionState::READY => {
+                        self.session.begin(VIEW_TYPE).map_err(frame_error("beginning the session"))?;
+                        self.running = true;
+                    }
+                    xr::SessionState::STOPPING => {
+                        self.session.end().map_err(frame_error("ending the session"))?;
+                        self.running = false;
+                    }
+                    xr::SessionState::EXITING | xr::SessionState::LOSS_PENDING => {
+                        self.running = false;
+                        self.exiting = true;
+                    }
+                    _ => {}
+                },
+                xr::Event::InstanceLossPending(_) => {
+                    self.running = false;
+                    self.exiting = true;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl XrSession for OpenXrSession {
+    /// The swapchain's format, or until one is created the runtime's
+    /// preferred format the renderer could use
+    fn format(&self) -> wgpu::TextureFormat {
+        if let Some(swapchain) = &self.swapchain {
+            return swapchain.format;
+        }
+        self.formats
+            .iter()
+            .find_map(|&raw| {
+                FORMATS
+                    .iter()
+                    .find(|(f, vk)| f.is_srgb() && vk.as_raw() as u32 == raw)
+                    .map(|(f, _)| *f)
+            })
+            .unwrap_or(wgpu::TextureFormat::Bgra8UnormSrgb)
+    }
+
+    fn wait_frame(&mut self) -> RenderResult<Option<XrFrame>> {
+        self.poll_events()?;
+        if !self.running {
+            return Ok(None);
+        }
+        let size = self.swapchain.as_ref().map(|swapchain| swapchain.size).ok_or_else(|| {
+            RenderError::RenderFailure("no OpenXR swapchain; call create_swapchain first".to_string())
+        })?;
+
+        let state = self.waiter.wait().map_err(frame_error("waiting for a frame"))?;
+        self.stream.begin().map_err(frame_error("beginning a frame"))?;
+        let time = state.predicted_display_time;
+        if !state.should_render {
+            self.stream
+                .end(time, self.blend_mode, &[])
+                .map_err(frame_error("skipping a frame"))?;
+            return Ok(None);
+        }
+
+        self.session
+            .sync_actions(&[xr::ActiveActionSet::new(&self.controls.set)])
+            .map_err(frame_error("syncing controllers"))?;
+        self.controllers = self
+            .controls
+            .read(&self.session, &self.stage, time)
+            .map_err(frame_error("reading controllers"))?;
+        let (_, views) = self
+            .session
+            .locate_views(VIEW_TYPE, time, &self.stage)
+            .map_err(frame_error("locating the eyes"))?;
+        let [left, right] = match &views[..] {
+            [left, right, ..] => [view(left), view(right)],
+            _ => {
+                let message = "the headset reported fewer than two views".to_string();
+                return Err(RenderError::RenderFailure(message));
+            }
+        };
+        self.pending = Some(PendingFrame { time, views });
+        Ok(Some(XrFrame {
+            display_time: time.as_nanos(),
+            views: [left, right],
+            size,
+        }))
+    }
+
+    fn acquire_image(&mut self, eye: Eye) -> RenderResult<wgpu::TextureView> {
+        let swapchain = self
+            .swapchain
+            .as_mut()
+            .ok_or_else(|| RenderError::RenderFailure("no OpenXR swapchain".to_string()))?;
+        // Both eyes are layers of the same image
+        let index = match swapchain.acquired {
+            Some(index) => index,
+            None => {
+                let index = swapchain.handle.acquire_image().map_err(frame_error("acquiring an image"))?;
+                swapchain
+                    .handle
+                    .wait_image(xr::Duration::INFINITE)
+                    .map_err(frame_error("waiting for an image"))?;
+                swapchain.acquired = Some(index as usize);
+                index as usize
+            }
+        };
+        Ok(swapchain.textures[index].create_view(&wgpu::TextureViewDescriptor {
+            label: Some("XR Eye View"),
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            base_array_layer: layer(eye),
+            array_layer_count: Some(1),
+            ..Default::default()
+        }))
+    }
+
+    fn end_frame(&mut self, _frame: &XrFrame) -> RenderResult<()> {
+        let pending = self
+            .pending
+            .take()
+            .ok_or_else(|| RenderError::RenderFailure("end_frame without wait_frame".to_string()))?;
+        let swapchain = self
+            .swapchain
+            .as_mut()
+            .ok_or_else(|| RenderError::RenderFailure("no OpenXR swapchain".to_string()))?;
+        if swapchain.acquired.take().is_some() {
+            swapchain.handle.release_image().map_err(frame_error("releasing an image"))?;
+        }
+
+        let rect = xr::Rect2Di {
+            offset: xr::Offset2Di { x: 0, y: 0 },
+            extent: xr::Extent2Di {
+                width: swapchain.size.0 as i32,
+                height: swapchain.size.1 as i32,
+            },
+        };
+        let views = Eye::BOTH.map(|eye| {
+            let view = &pending.views[layer(eye) as usize];
+            xr::CompositionLayerProjectionView::new()
+                .pose(view.pose)
+                .fov(view.fov)
+                .sub_image(
+                    xr::SwapchainSubImage::new()
+                        .swapchain(&swapchain.handle)
+                        .image_array_index(layer(eye))
+                        .image_rect(rect),
+                )
+        });
+        let projection = xr::CompositionLayerProjection::new().space(&self.stage).views(&views);
+        self.stream
+            .end(pending.time, self.blend_mode, &[&projection])
+            .map_err(frame_error("ending a frame"))
+    }
+
+    fn controller(&self, hand: Hand) -> Option<ControllerState> {
+        match hand {
+            Hand::Left => self.controllers[0],
+            Hand::Right => self.controllers[1],
+        }
+    }
+}
+
+/// Swapchain layer, and index into located views, of an eye
+fn layer(eye: Eye) -> u32 {
+    match eye {
+        Eye::Left => 0,
+        Eye::Right => 1,
+    }
+}
+
+fn pose(pose: xr::Posef) -> XrPose {
+    let (p, o) = (pose.position, pose.orientation);
+    XrPose {
+        position: Point3::new(p.x, p.y, p.z),
+        orientation: UnitQuaternion::new_normalize(Quaternion::new(o.w, o.x, o.y, o.z)),
+    }
+}
+
+fn view(view: &xr::View) -> XrView {
+    XrView {
+        pose: pose(view.pose),
+        fov: XrFov {
+            angle_left: view.fov.angle_left,
+            angle_right: view.fov.angle_right,
+            angle_up: view.fov.angle_up,
+            angle_down: view.fov.angle_down,
+        },
+    }
+}
+
+fn init_error<E: Display>(what: &'static str) -> impl Fn(E) -> RenderError {
+    move |e| RenderError::InitializationError(format!("OpenXR: {}: {}", what, e))
+}
+
+fn frame_error<E: Display>(what: &'static str) -> impl Fn(E) -> RenderError {
+    move |e| RenderError::RenderFailure(format!("OpenXR: {}: {}", what, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn test_view_conversion() {
+        // Half a turn about Y, looking down +Z
+        let located = xr::View {
+            pose: xr::Posef {
+                orientation: xr::Quaternionf { x: 0.0, y: 1.0, z: 0.0, w: 0.0 },
+                position: xr::Vector3f { x: 0.1, y: 1.6, z: 0.0 },
+            },
+            fov: xr::Fovf {
+                angle_left: -0.8,
+                angle_right: 0.7,
+                angle_up: 0.75,
+                angle_down: -0.9,
+            },
+        };
+        let converted = view(&located);
+        assert_eq!(converted.pose.position, Point3::new(0.1, 1.6, 0.0));
+        assert!((converted.pose.forward() - Vector3::z()).norm() < 1e-6);
+        assert_eq!(converted.fov.angle_down, -0.9);
+        assert_eq!([layer(Eye::Left), layer(Eye::Right)], [0, 1]);
+    }
+}
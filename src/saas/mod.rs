@@ -177,6 +177,10 @@ pub enum SaasError {
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 
+    /// Query builder error, including optimistic locking conflicts
+    #[error("Query error: {0}")]
+    Query(#[from] crate::database::DatabaseError),
+
     /// Serialization error
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
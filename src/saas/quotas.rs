@@ -659,6 +659,7 @@ impl QuotaManager {
             (SubscriptionTier::Free, UsageMetric::Render) => 1_000,
             (SubscriptionTier::Free, UsageMetric::Export) => 100,
             (SubscriptionTier::Free, UsageMetric::Collaboration) => 0,
+            (SubscriptionTier::Free, UsageMetric::Compute) => 36_000, // 10 hours
 
             // Pro tier limits
             (SubscriptionTier::Pro, UsageMetric::ApiCall) => 100_000,
@@ -669,6 +670,7 @@ impl QuotaManager {
             (SubscriptionTier::Pro, UsageMetric::Render) => 10_000,
             (SubscriptionTier::Pro, UsageMetric::Export) => 1_000,
             (SubscriptionTier::Pro, UsageMetric::Collaboration) => 10,
+            (SubscriptionTier::Pro, UsageMetric::Compute) => 360_000, // 100 hours
 
             // Enterprise tier limits (unlimited or very high)
             (SubscriptionTier::Enterprise, UsageMetric::ApiCall) => 1_000_000,
@@ -679,6 +681,7 @@ impl QuotaManager {
             (SubscriptionTier::Enterprise, UsageMetric::Render) => 100_000,
             (SubscriptionTier::Enterprise, UsageMetric::Export) => 10_000,
             (SubscriptionTier::Enterprise, UsageMetric::Collaboration) => 100,
+            (SubscriptionTier::Enterprise, UsageMetric::Compute) => 3_600_000, // 1,000 hours
         }
     }
 }
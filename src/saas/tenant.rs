@@ -38,10 +38,11 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{PgPool, Row};
+use sqlx::{PgPool, Postgres, Row};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::database::query_builder::{Delete, Filtered, Insert, Page, Paginated, Select, Update};
 use crate::saas::{Result, SaasError};
 
 // ============================================================================
@@ -279,30 +280,22 @@ impl TenantManager {
         let branding = config.branding.unwrap_or_default();
         let metadata: HashMap<String, serde_json::Value> = HashMap::new();
 
-        let tenant = sqlx::query_as::<_, Tenant>(
-            r"
-            INSERT INTO tenants (
-                id, slug, name, status, custom_domain, branding, metadata, settings,
-                max_users, max_storage_bytes, created_at, updated_at
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
-            RETURNING *
-            ",
-        )
-        .bind(tenant_id)
-        .bind(&config.slug)
-        .bind(&config.name)
-        .bind(TenantStatus::Provisioning)
-        .bind(&config.custom_domain)
-        .bind(serde_json::to_value(&branding)?)
-        .bind(serde_json::to_value(&metadata)?)
-        .bind(serde_json::to_value(&settings)?)
-        .bind(max_users)
-        .bind(max_storage_bytes)
-        .bind(Utc::now())
-        .bind(Utc::now())
-        .fetch_one(&self.pool)
-        .await?;
+        let now = Utc::now();
+        let mut insert = Insert::<Postgres>::into("tenants")
+            .value("id", tenant_id)
+            .value("slug", config.slug.as_str())
+            .value("name", config.name.as_str())
+            .value("status", TenantStatus::Provisioning)
+            .value("custom_domain", config.custom_domain.clone())
+            .value("branding", serde_json::to_value(&branding)?)
+            .value("metadata", serde_json::to_value(&metadata)?)
+            .value("settings", serde_json::to_value(&settings)?)
+            .value("max_users", max_users)
+            .value("max_storage_bytes", max_storage_bytes)
+            .value("created_at", now)
+            .value("updated_at", now)
+            .build()?;
+        insert.build().execute(&self.pool).await?;
 
         // Initialize tenant database schema (row-level security)
         self.initialize_tenant_schema(tenant_id).await?;
@@ -315,79 +308,62 @@ impl TenantManager {
 
     /// Get tenant by ID
     pub async fn get_tenant(&self, tenant_id: Uuid) -> Result<Tenant> {
-        sqlx::query_as::<_, Tenant>(
-            r"
-            SELECT * FROM tenants
-            WHERE id = $1 AND deleted_at IS NULL
-            ",
-        )
-        .bind(tenant_id)
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|_| SaasError::TenantNotFound(tenant_id.to_string()))
+        let mut query = Self::live_tenants().where_eq("id", tenant_id).build()?;
+        query
+            .build_query_as::<Tenant>()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| SaasError::TenantNotFound(tenant_id.to_string()))
     }
 
     /// Get tenant by slug
     pub async fn get_tenant_by_slug(&self, slug: &str) -> Result<Tenant> {
-        sqlx::query_as::<_, Tenant>(
-            r"
-            SELECT * FROM tenants
-            WHERE slug = $1 AND deleted_at IS NULL
-            ",
-        )
-        .bind(slug)
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|_| SaasError::TenantNotFound(slug.to_string()))
+        let mut query = Self::live_tenants().where_eq("slug", slug).build()?;
+        query
+            .build_query_as::<Tenant>()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| SaasError::TenantNotFound(slug.to_string()))
     }
 
     /// Get tenant by custom domain
     pub async fn get_tenant_by_domain(&self, domain: &str) -> Result<Tenant> {
-        sqlx::query_as::<_, Tenant>(
-            r"
-            SELECT * FROM tenants
-            WHERE custom_domain = $1 AND deleted_at IS NULL
-            ",
-        )
-        .bind(domain)
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|_| SaasError::TenantNotFound(format!("domain: {}", domain)))
+        let mut query = Self::live_tenants()
+            .where_eq("custom_domain", domain)
+            .build()?;
+        query
+            .build_query_as::<Tenant>()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| SaasError::TenantNotFound(format!("domain: {}", domain)))
     }
 
     /// List all active tenants
     pub async fn list_tenants(&self, limit: i64, offset: i64) -> Result<Vec<Tenant>> {
-        sqlx::query_as::<_, Tenant>(
-            r"
-            SELECT * FROM tenants
-            WHERE deleted_at IS NULL
-            ORDER BY created_at DESC
-            LIMIT $1 OFFSET $2
-            ",
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(SaasError::Database)
+        let mut query = Self::live_tenants()
+            .order_desc("created_at")
+            .limit(limit.max(0) as u64)
+            .offset(offset.max(0) as u64)
+            .build()?;
+        query
+            .build_query_as::<Tenant>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(SaasError::Database)
+    }
+
+    /// List active tenants one page at a time, newest first
+    pub async fn list_tenants_page(&self, page: Page) -> Result<Paginated<Tenant>> {
+        Ok(Self::live_tenants()
+            .order_desc("created_at")
+            .fetch_page(&self.pool, page)
+            .await?)
     }
 
     /// Update tenant status
     pub async fn update_status(&self, tenant_id: Uuid, status: TenantStatus) -> Result<()> {
-        sqlx::query(
-            r"
-            UPDATE tenants
-            SET status = $1, updated_at = $2
-            WHERE id = $3
-            ",
-        )
-        .bind(status)
-        .bind(Utc::now())
-        .bind(tenant_id)
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+        self.update_tenant(tenant_id, Update::table("tenants").set("status", status))
+            .await
     }
 
     /// Set custom domain for tenant
@@ -405,20 +381,11 @@ impl TenantManager {
             )));
         }
 
-        sqlx::query(
-            r"
-            UPDATE tenants
-            SET custom_domain = $1, updated_at = $2
-            WHERE id = $3
-            ",
+        self.update_tenant(
+            tenant_id,
+            Update::table("tenants").set("custom_domain", domain),
         )
-        .bind(domain)
-        .bind(Utc::now())
-        .bind(tenant_id)
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+        .await
     }
 
     /// Update branding settings
@@ -427,20 +394,11 @@ impl TenantManager {
         tenant_id: Uuid,
         branding: BrandingSettings,
     ) -> Result<()> {
-        sqlx::query(
-            r"
-            UPDATE tenants
-            SET branding = $1, updated_at = $2
-            WHERE id = $3
-            ",
+        self.update_tenant(
+            tenant_id,
+            Update::table("tenants").set("branding", serde_json::to_value(&branding)?),
         )
-        .bind(serde_json::to_value(&branding)?)
-        .bind(Utc::now())
-        .bind(tenant_id)
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+        .await
     }
 
     /// Update tenant settings
@@ -449,20 +407,11 @@ impl TenantManager {
         tenant_id: Uuid,
         settings: TenantSettings,
     ) -> Result<()> {
-        sqlx::query(
-            r"
-            UPDATE tenants
-            SET settings = $1, updated_at = $2
-            WHERE id = $3
-            ",
+        self.update_tenant(
+            tenant_id,
+            Update::table("tenants").set("settings", serde_json::to_value(&settings)?),
         )
-        .bind(serde_json::to_value(&settings)?)
-        .bind(Utc::now())
-        .bind(tenant_id)
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+        .await
     }
 
     /// Update tenant metadata
@@ -471,20 +420,11 @@ impl TenantManager {
         tenant_id: Uuid,
         metadata: HashMap<String, serde_json::Value>,
     ) -> Result<()> {
-        sqlx::query(
-            r"
-            UPDATE tenants
-            SET metadata = $1, updated_at = $2
-            WHERE id = $3
-            ",
+        self.update_tenant(
+            tenant_id,
+            Update::table("tenants").set("metadata", serde_json::to_value(&metadata)?),
         )
-        .bind(serde_json::to_value(&metadata)?)
-        .bind(Utc::now())
-        .bind(tenant_id)
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+        .await
     }
 
     /// Suspend a tenant
@@ -500,34 +440,22 @@ impl TenantManager {
 
     /// Soft delete a tenant
     pub async fn delete_tenant(&self, tenant_id: Uuid) -> Result<()> {
-        sqlx::query(
-            r"
-            UPDATE tenants
-            SET status = $1, deleted_at = $2, updated_at = $3
-            WHERE id = $4
-            ",
+        self.update_tenant(
+            tenant_id,
+            Update::table("tenants")
+                .set("status", TenantStatus::Deleted)
+                .set("deleted_at", Utc::now()),
         )
-        .bind(TenantStatus::Deleted)
-        .bind(Utc::now())
-        .bind(Utc::now())
-        .bind(tenant_id)
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+        .await
     }
 
     /// Permanently delete a tenant and all associated data
     pub async fn hard_delete_tenant(&self, tenant_id: Uuid) -> Result<()> {
         // This should cascade delete all tenant data
-        sqlx::query(
-            r"
-            DELETE FROM tenants WHERE id = $1
-            ",
-        )
-        .bind(tenant_id)
-        .execute(&self.pool)
-        .await?;
+        let mut delete = Delete::<Postgres>::from("tenants")
+            .where_eq("id", tenant_id)
+            .build()?;
+        delete.build().execute(&self.pool).await?;
 
         Ok(())
     }
@@ -566,6 +494,22 @@ impl TenantManager {
     // Private Helper Methods
     // ========================================================================
 
+    /// Tenants that have not been soft deleted
+    fn live_tenants<'a>() -> Select<'a, Postgres> {
+        Select::from("tenants").where_null("deleted_at")
+    }
+
+    /// Apply an update to one tenant, stamping `updated_at`
+    async fn update_tenant(&self, tenant_id: Uuid, update: Update<'_, Postgres>) -> Result<()> {
+        let mut query = update
+            .set("updated_at", Utc::now())
+            .where_eq("id", tenant_id)
+            .build()?;
+        query.build().execute(&self.pool).await?;
+
+        Ok(())
+    }
+
     /// Initialize tenant-specific database schema with row-level security
     async fn initialize_tenant_schema(&self, tenant_id: Uuid) -> Result<()> {
         // In a real implementation, this would set up:
@@ -6,6 +6,8 @@
 //! - Page scan metering
 //! - Storage usage monitoring
 //! - User seat tracking
+//! - Compute time metering
+//! - Per-team attribution within a tenant
 //! - Overage detection and handling
 //! - Usage aggregation and reporting
 //!
@@ -49,6 +51,9 @@ use uuid::Uuid;
 
 use crate::saas::{Result, SaasError};
 
+/// Usage record metadata key naming the team (workspace) the usage belongs to
+pub const TEAM_METADATA_KEY: &str = "team_id";
+
 // ============================================================================
 // Usage Metric Types
 // ============================================================================
@@ -73,6 +78,8 @@ pub enum UsageMetric {
     Export,
     /// Collaboration session
     Collaboration,
+    /// Compute time (seconds)
+    Compute,
 }
 
 impl UsageMetric {
@@ -87,6 +94,7 @@ impl UsageMetric {
             Self::Render => "Renders",
             Self::Export => "Exports",
             Self::Collaboration => "Collaboration Sessions",
+            Self::Compute => "Compute",
         }
     }
 
@@ -101,6 +109,7 @@ impl UsageMetric {
             Self::Render => "renders",
             Self::Export => "exports",
             Self::Collaboration => "sessions",
+            Self::Compute => "seconds",
         }
     }
 }
@@ -174,6 +183,9 @@ pub struct UsageStats {
     /// Collaboration sessions
     pub collaboration_sessions: i64,
 
+    /// Compute seconds
+    pub compute_seconds: i64,
+
     /// Overage flags
     pub has_overage: bool,
 
@@ -206,6 +218,22 @@ pub struct OverageInfo {
     pub total_overage_cents: i64,
 }
 
+/// Usage of one metric attributed to one team over a period
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TeamUsageTotal {
+    /// Team (workspace) ID; `None` for usage not attributed to a team
+    pub team_id: Option<String>,
+
+    /// Usage metric
+    pub metric: UsageMetric,
+
+    /// Total amount
+    pub total_amount: i64,
+
+    /// Record count
+    pub record_count: i64,
+}
+
 /// Daily usage aggregate
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct DailyUsageAggregate {
@@ -354,6 +382,33 @@ impl UsageManager {
             .await
     }
 
+    /// Record usage attributed to a team within the tenant
+    pub async fn record_team_usage(
+        &self,
+        tenant_id: Uuid,
+        team_id: &str,
+        metric: UsageMetric,
+        amount: i64,
+        resource_id: Option<String>,
+    ) -> Result<UsageRecord> {
+        let mut metadata = HashMap::new();
+        metadata.insert(TEAM_METADATA_KEY.to_string(), serde_json::json!(team_id));
+
+        self.record_usage_with_metadata(tenant_id, metric, amount, resource_id, metadata)
+            .await
+    }
+
+    /// Record compute time
+    pub async fn record_compute(
+        &self,
+        tenant_id: Uuid,
+        job_id: String,
+        seconds: i64,
+    ) -> Result<UsageRecord> {
+        self.record_usage_with_resource(tenant_id, UsageMetric::Compute, seconds, job_id)
+            .await
+    }
+
     // ========================================================================
     // Usage Retrieval
     // ========================================================================
@@ -412,6 +467,9 @@ impl UsageManager {
         let collaboration_sessions = self
             .get_metric_total(tenant_id, UsageMetric::Collaboration, period_start, period_end)
             .await?;
+        let compute_seconds = self
+            .get_metric_total(tenant_id, UsageMetric::Compute, period_start, period_end)
+            .await?;
 
         // Check for overages
         let overages = self.check_overages(tenant_id, &UsageStats {
@@ -426,6 +484,7 @@ impl UsageManager {
             renders,
             exports,
             collaboration_sessions,
+            compute_seconds,
             has_overage: false,
             overages: Vec::new(),
         }).await?;
@@ -442,6 +501,7 @@ impl UsageManager {
             renders,
             exports,
             collaboration_sessions,
+            compute_seconds,
             has_overage: !overages.is_empty(),
             overages,
         })
@@ -475,6 +535,40 @@ impl UsageManager {
         Ok(row.try_get("total")?)
     }
 
+    /// Get per-team usage totals for a period
+    ///
+    /// Usage recorded without a team comes back with `team_id: None`, so the
+    /// rows always add up to the tenant's totals.
+    pub async fn get_team_usage_for_period(
+        &self,
+        tenant_id: Uuid,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<Vec<TeamUsageTotal>> {
+        sqlx::query_as::<_, TeamUsageTotal>(
+            r"
+            SELECT
+                metadata->>$4 as team_id,
+                metric,
+                COALESCE(SUM(amount), 0)::BIGINT as total_amount,
+                COUNT(*) as record_count
+            FROM usage_records
+            WHERE tenant_id = $1
+            AND recorded_at >= $2
+            AND recorded_at < $3
+            GROUP BY metadata->>$4, metric
+            ORDER BY team_id, metric
+            ",
+        )
+        .bind(tenant_id)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(TEAM_METADATA_KEY)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SaasError::Database)
+    }
+
     /// Get usage records for tenant
     pub async fn get_usage_records(
         &self,
@@ -516,6 +610,7 @@ impl UsageManager {
             UsageMetric::Render,
             UsageMetric::Export,
             UsageMetric::Collaboration,
+            UsageMetric::Compute,
         ] {
             sqlx::query(
                 r"
@@ -655,6 +750,7 @@ mod tests {
         assert_eq!(UsageMetric::ApiCall.unit(), "calls");
         assert_eq!(UsageMetric::Storage.unit(), "bytes");
         assert_eq!(UsageMetric::UserSeat.unit(), "seats");
+        assert_eq!(UsageMetric::Compute.unit(), "seconds");
     }
 
     #[test]
@@ -145,6 +145,8 @@ pub mod queue;
 pub mod worker;
 pub mod monitor;
 pub mod notifications;
+pub mod trash;
+pub mod seats;
 
 // Re-export commonly used types
 pub use scheduler::{
@@ -172,3 +174,10 @@ pub use notifications::{
     NotificationSeverity, NotificationService, QuietHours, SlackConfig, SlackDelivery,
     TeamsConfig, TeamsDelivery, WebhookConfig, WebhookDelivery,
 };
+
+pub use trash::{TrashPurgeExecutor, TRASH_PURGE_ACTOR, TRASH_PURGE_JOB_TYPE};
+
+pub use seats::{
+    SeatReclamationExecutor, SeatReportExecutor, SEAT_RECLAMATION_JOB_TYPE,
+    SEAT_RECLAMATION_SOURCE, SEAT_REPORT_JOB_TYPE, SEAT_REPORT_SOURCE,
+};
@@ -16,6 +16,8 @@ use std::sync::Arc;
 use thiserror::Error;
 use tokio::sync::RwLock;
 
+use crate::enterprise::tracing::TracedSend;
+
 /// Notification errors
 #[derive(Error, Debug)]
 pub enum NotificationError {
@@ -353,7 +355,7 @@ impl NotificationDelivery for SlackDelivery {
             .client
             .post(&self.config.webhook_url)
             .json(&payload)
-            .send()
+            .send_traced("notification.slack")
             .await?;
 
         if response.status().is_success() {
@@ -462,7 +464,7 @@ impl NotificationDelivery for TeamsDelivery {
             .client
             .post(&self.config.webhook_url)
             .json(&payload)
-            .send()
+            .send_traced("notification.teams")
             .await?;
 
         if response.status().is_success() {
@@ -535,7 +537,7 @@ impl NotificationDelivery for WebhookDelivery {
         let response = request
             .json(&payload)
             .timeout(std::time::Duration::from_secs(self.config.timeout_seconds))
-            .send()
+            .send_traced("notification.webhook")
             .await?;
 
         if response.status().is_success() {
@@ -0,0 +1,309 @@
+//! License seat jobs
+//!
+//! Two recurring jobs keep licensed seats in use. The reclamation job runs
+//! daily: it closes reclamation cases whose notice period has ended, opens
+//! cases for newly idle seats and notifies the affected holders. The report
+//! job runs on the first of each month and sends administrators a seat usage
+//! report for the previous month through the notification service, which
+//! delivers it by email where that channel is enabled.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use caddy::enterprise::licensing::{EntitlementManager, License, ReclamationPolicy, SeatManager};
+//! use caddy::scheduling::notifications::NotificationService;
+//! use caddy::scheduling::scheduler::JobScheduler;
+//! use caddy::scheduling::seats::{SeatReclamationExecutor, SeatReportExecutor};
+//! use std::sync::Arc;
+//! use tokio::sync::RwLock;
+//!
+//! # async fn example(license: License) -> Result<(), Box<dyn std::error::Error>> {
+//! let seats = Arc::new(RwLock::new(SeatManager::new(
+//!     EntitlementManager::new(&license),
+//!     ReclamationPolicy::default(),
+//! )));
+//! let notifications = Arc::new(NotificationService::new());
+//!
+//! let scheduler = JobScheduler::new("redis://localhost").await?;
+//! scheduler
+//!     .register_executor(Arc::new(SeatReclamationExecutor::new(seats.clone(), notifications.clone())))
+//!     .await;
+//! scheduler
+//!     .register_executor(Arc::new(SeatReportExecutor::new(seats, notifications)))
+//!     .await;
+//! scheduler.schedule_job(SeatReclamationExecutor::job()).await?;
+//! scheduler.schedule_job(SeatReportExecutor::job()).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use super::notifications::{Notification, NotificationService, NotificationSeverity};
+use super::scheduler::{
+    Job, JobExecutor, JobPriority, JobSchedule, SchedulerError, SchedulerResult,
+};
+use crate::enterprise::licensing::seats::{ReclamationCase, ReportPeriod, SeatManager, SeatReport};
+
+/// Job type handled by [`SeatReclamationExecutor`]
+pub const SEAT_RECLAMATION_JOB_TYPE: &str = "license-seat-reclamation";
+
+/// Job type handled by [`SeatReportExecutor`]
+pub const SEAT_REPORT_JOB_TYPE: &str = "license-seat-report";
+
+/// Notification source for reclamation notices; subscribe seat holders'
+/// channels to it
+pub const SEAT_RECLAMATION_SOURCE: &str = "licensing.seats.reclamation";
+
+/// Notification source for monthly seat reports; subscribe administrators'
+/// channels to it
+pub const SEAT_REPORT_SOURCE: &str = "licensing.seats.report";
+
+/// Advances the idle-seat reclamation workflow and notifies seat holders
+pub struct SeatReclamationExecutor {
+    seats: Arc<RwLock<SeatManager>>,
+    notifications: Arc<NotificationService>,
+}
+
+impl SeatReclamationExecutor {
+    /// Create an executor for shared seats and a notification service
+    pub fn new(seats: Arc<RwLock<SeatManager>>, notifications: Arc<NotificationService>) -> Self {
+        Self {
+            seats,
+            notifications,
+        }
+    }
+
+    /// Daily job definition, run at 06:00 UTC
+    pub fn job() -> Job {
+        let mut job = Job::new(
+            "license-seat-reclamation".to_string(),
+            SEAT_RECLAMATION_JOB_TYPE.to_string(),
+            JobSchedule::Cron("0 0 6 * * *".to_string()),
+        );
+        job.priority = JobPriority::Low;
+        job
+    }
+
+    /// Close due cases, open new ones and notify holders of new cases.
+    /// Returns the cases opened.
+    pub async fn run(&self, now: DateTime<Utc>) -> SchedulerResult<Vec<ReclamationCase>> {
+        let opened = {
+            let mut seats = self.seats.write().await;
+            seats.advance(now);
+            seats.review(now)
+        };
+
+        for case in &opened {
+            let notification = Notification::new(
+                "Your license seat is about to be reclaimed".to_string(),
+                format!(
+                    "{}. Your seat will be released on {} unless you use the application before then.",
+                    case.reason,
+                    case.reclaim_after.format("%Y-%m-%d")
+                ),
+                SEAT_RECLAMATION_SOURCE.to_string(),
+            )
+            .with_severity(NotificationSeverity::Warning)
+            .with_metadata("case_id".to_string(), serde_json::json!(case.id))
+            .with_metadata("user_id".to_string(), serde_json::json!(case.user_id))
+            .with_metadata(
+                "reclaim_after".to_string(),
+                serde_json::json!(case.reclaim_after),
+            );
+
+            self.notifications
+                .send(notification)
+                .await
+                .map_err(|e| SchedulerError::ExecutionError(e.to_string()))?;
+        }
+
+        Ok(opened)
+    }
+}
+
+#[async_trait]
+impl JobExecutor for SeatReclamationExecutor {
+    async fn execute(&self, _job: &Job) -> SchedulerResult<()> {
+        self.run(Utc::now()).await.map(|_| ())
+    }
+
+    fn job_type(&self) -> &str {
+        SEAT_RECLAMATION_JOB_TYPE
+    }
+}
+
+/// Sends the monthly seat usage report
+pub struct SeatReportExecutor {
+    seats: Arc<RwLock<SeatManager>>,
+    notifications: Arc<NotificationService>,
+}
+
+impl SeatReportExecutor {
+    /// Create an executor for shared seats and a notification service
+    pub fn new(seats: Arc<RwLock<SeatManager>>, notifications: Arc<NotificationService>) -> Self {
+        Self {
+            seats,
+            notifications,
+        }
+    }
+
+    /// Monthly job definition, run at 08:00 UTC on the first of the month
+    pub fn job() -> Job {
+        let mut job = Job::new(
+            "license-seat-report".to_string(),
+            SEAT_REPORT_JOB_TYPE.to_string(),
+            JobSchedule::Cron("0 0 8 1 * *".to_string()),
+        );
+        job.priority = JobPriority::Low;
+        job
+    }
+
+    /// Report on the month before `now` and send it
+    pub async fn send_report(&self, now: DateTime<Utc>) -> SchedulerResult<SeatReport> {
+        let report = self
+            .seats
+            .read()
+            .await
+            .report(ReportPeriod::previous_month(now), now);
+
+        let severity = if report.suggestions.is_empty() {
+            NotificationSeverity::Info
+        } else {
+            NotificationSeverity::Warning
+        };
+        let notification = Notification::new(
+            format!(
+                "License seat report for {}",
+                report.period.start.format("%B %Y")
+            ),
+            report.summary(),
+            SEAT_REPORT_SOURCE.to_string(),
+        )
+        .with_severity(severity)
+        .with_metadata("report".to_string(), serde_json::to_value(&report)?);
+
+        self.notifications
+            .send(notification)
+            .await
+            .map_err(|e| SchedulerError::ExecutionError(e.to_string()))?;
+
+        Ok(report)
+    }
+}
+
+#[async_trait]
+impl JobExecutor for SeatReportExecutor {
+    async fn execute(&self, _job: &Job) -> SchedulerResult<()> {
+        self.send_report(Utc::now()).await.map(|_| ())
+    }
+
+    fn job_type(&self) -> &str {
+        SEAT_REPORT_JOB_TYPE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enterprise::licensing::entitlement::EntitlementManager;
+    use crate::enterprise::licensing::license::{License, LicenseType, LicenseeInfo};
+    use crate::enterprise::licensing::seats::ReclamationPolicy;
+    use crate::scheduling::notifications::{
+        NotificationChannel, NotificationDelivery, NotificationPreferences, NotificationPriority,
+        NotificationResult,
+    };
+    use chrono::{Duration, TimeZone};
+
+    struct Outbox(std::sync::Mutex<Vec<Notification>>);
+
+    #[async_trait]
+    impl NotificationDelivery for Outbox {
+        async fn deliver(&self, notification: &Notification) -> NotificationResult<()> {
+            self.0.lock().unwrap().push(notification.clone());
+            Ok(())
+        }
+
+        fn channel_type(&self) -> NotificationChannel {
+            NotificationChannel::Email
+        }
+
+        async fn test(&self) -> NotificationResult<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reclamation_notices_and_monthly_report() {
+        let license = License::new(
+            "TEST-KEY-123".to_string(),
+            LicenseType::Professional,
+            LicenseeInfo {
+                name: "Test User".to_string(),
+                email: "test@example.com".to_string(),
+                organization: None,
+                country: None,
+            },
+        );
+        let mut manager = SeatManager::new(
+            EntitlementManager::new(&license),
+            ReclamationPolicy::default(),
+        );
+        manager.entitlements.seats.named_users = Some(vec!["alice".to_string(), "bob".to_string()]);
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        manager
+            .entitlements
+            .analytics
+            .record_activity("alice", start);
+        manager
+            .entitlements
+            .analytics
+            .record_activity("bob", start + Duration::days(20));
+        let seats = Arc::new(RwLock::new(manager));
+
+        let outbox = Arc::new(Outbox(std::sync::Mutex::new(Vec::new())));
+        let notifications = Arc::new(NotificationService::new());
+        notifications.register_channel(outbox.clone()).await;
+        notifications
+            .set_preferences(NotificationPreferences {
+                user_id: "admin".to_string(),
+                enabled_channels: vec![NotificationChannel::Email],
+                min_severity: NotificationSeverity::Info,
+                min_priority: NotificationPriority::Low,
+                quiet_hours: None,
+                source_filters: vec![
+                    SEAT_RECLAMATION_SOURCE.to_string(),
+                    SEAT_REPORT_SOURCE.to_string(),
+                ],
+            })
+            .await;
+
+        let now = Utc.with_ymd_and_hms(2024, 4, 1, 8, 0, 0).unwrap();
+        let opened = SeatReclamationExecutor::new(seats.clone(), notifications.clone())
+            .run(now)
+            .await
+            .unwrap();
+        assert_eq!(opened.len(), 1);
+        assert_eq!(opened[0].user_id, "alice");
+
+        let report = SeatReportExecutor::new(seats, notifications)
+            .send_report(now)
+            .await
+            .unwrap();
+        assert_eq!(
+            report.period.start,
+            Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(report.seats.len(), 2);
+
+        let sent = outbox.0.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].metadata["user_id"], "alice");
+        assert_eq!(sent[1].title, "License seat report for March 2024");
+        assert!(sent[1].message.contains("- alice: No activity for 30 days"));
+        assert!(sent[1].metadata.contains_key("report"));
+    }
+}
@@ -0,0 +1,153 @@
+//! Recycle bin retention job
+//!
+//! Periodically purges recycle bin items whose retention window has passed
+//! and writes one compliance audit entry per purged item, so permanent
+//! deletions stay accountable after the data itself is gone.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use caddy::enterprise::compliance::trail::AuditTrail;
+//! use caddy::io::trash::{RecycleBin, TrashSettings};
+//! use caddy::scheduling::scheduler::JobScheduler;
+//! use caddy::scheduling::trash::TrashPurgeExecutor;
+//! use std::sync::Arc;
+//! use tokio::sync::RwLock;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let bin = Arc::new(RwLock::new(RecycleBin::new(TrashSettings::default())));
+//! let trail = Arc::new(AuditTrail::new());
+//!
+//! let scheduler = JobScheduler::new("redis://localhost").await?;
+//! scheduler
+//!     .register_executor(Arc::new(TrashPurgeExecutor::new(bin, trail)))
+//!     .await;
+//! scheduler.schedule_job(TrashPurgeExecutor::job(3600)).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use super::scheduler::{Job, JobExecutor, JobPriority, JobSchedule, SchedulerError, SchedulerResult};
+use crate::enterprise::compliance::trail::{AuditEntryBuilder, AuditTrail};
+use crate::io::trash::{PurgeRecord, RecycleBin};
+
+/// Job type handled by [`TrashPurgeExecutor`]
+pub const TRASH_PURGE_JOB_TYPE: &str = "trash-purge";
+
+/// Actor recorded on audit entries written by the retention job
+pub const TRASH_PURGE_ACTOR: &str = "system:trash-retention";
+
+/// Purges expired recycle bin items and records each purge in the audit trail
+pub struct TrashPurgeExecutor {
+    bin: Arc<RwLock<RecycleBin>>,
+    trail: Arc<AuditTrail>,
+}
+
+impl TrashPurgeExecutor {
+    /// Create an executor for a shared recycle bin and audit trail
+    pub fn new(bin: Arc<RwLock<RecycleBin>>, trail: Arc<AuditTrail>) -> Self {
+        Self { bin, trail }
+    }
+
+    /// Recurring job definition running every `interval_seconds`
+    pub fn job(interval_seconds: i64) -> Job {
+        let mut job = Job::new(
+            "recycle-bin-retention".to_string(),
+            TRASH_PURGE_JOB_TYPE.to_string(),
+            JobSchedule::Interval {
+                duration: interval_seconds,
+                start: None,
+            },
+        );
+        job.priority = JobPriority::Low;
+        job
+    }
+
+    /// Purge everything expired at `now` and audit it
+    pub async fn purge(&self, now: DateTime<Utc>) -> SchedulerResult<Vec<PurgeRecord>> {
+        let purged = self.bin.write().await.purge_expired(now);
+
+        for record in &purged {
+            let mut entry = AuditEntryBuilder::new(
+                TRASH_PURGE_ACTOR,
+                "trash.purge",
+                format!("{}/{}", record.kind, record.object_id),
+            )
+            .timestamp(record.purged_at)
+            .metadata("trash_item", record.item_id.to_string())
+            .metadata("deleted_by", record.deleted_by.clone())
+            .metadata("deleted_at", record.deleted_at.to_rfc3339())
+            .metadata("expires_at", record.expires_at.to_rfc3339());
+            if let Some(document_id) = record.document_id {
+                entry = entry.metadata("document", document_id.to_string());
+            }
+
+            self.trail
+                .append(entry)
+                .await
+                .map_err(SchedulerError::ExecutionError)?;
+        }
+
+        Ok(purged)
+    }
+}
+
+#[async_trait]
+impl JobExecutor for TrashPurgeExecutor {
+    async fn execute(&self, _job: &Job) -> SchedulerResult<()> {
+        self.purge(Utc::now()).await.map(|_| ())
+    }
+
+    fn job_type(&self) -> &str {
+        TRASH_PURGE_JOB_TYPE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{Document, Entity, GeometryType, Point, Vec3};
+    use crate::io::trash::TrashSettings;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn test_purge_records_audit_entries() {
+        let mut doc = Document::new();
+        let entity = Entity::new(
+            GeometryType::Point(Point {
+                position: Vec3::zero(),
+            }),
+            "0".to_string(),
+        );
+        let entity_id = entity.id;
+        doc.add_entity(entity);
+
+        let mut bin = RecycleBin::new(TrashSettings { retention_days: 1 });
+        bin.delete_entity(&mut doc, entity_id, "alice", None).unwrap();
+        bin.delete_document(doc, "alice", None);
+
+        let bin = Arc::new(RwLock::new(bin));
+        let trail = Arc::new(AuditTrail::new());
+        let executor = TrashPurgeExecutor::new(bin.clone(), trail.clone());
+
+        assert!(executor.purge(Utc::now()).await.unwrap().is_empty());
+
+        let purged = executor.purge(Utc::now() + Duration::days(2)).await.unwrap();
+        assert_eq!(purged.len(), 2);
+        assert!(bin.read().await.is_empty());
+
+        let entries = trail.get_entries_by_actor(TRASH_PURGE_ACTOR).await;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].action, "trash.purge");
+        assert_eq!(entries[0].resource, format!("entity/{}", entity_id));
+        assert_eq!(entries[0].metadata["deleted_by"], "alice");
+        assert!(entries[0].metadata.contains_key("document"));
+        assert!(entries[1].resource.starts_with("document/"));
+        assert!(trail.verify_chain().await.is_ok());
+    }
+}
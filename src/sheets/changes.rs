@@ -0,0 +1,539 @@
+//! Change requests from review markups
+//!
+//! Reviewers leave [`Markup`]s on sheets. Markups are grouped into a
+//! [`ChangeRequest`], which is assigned to someone and moves through
+//! open, in progress, implemented, and verified. Issuing a sheet revision
+//! with [`ChangeLog::issue_revision`] writes the revision into the sheet's
+//! title block fields and links it to every implemented request with
+//! markups on that sheet, so each request records the revisions that
+//! resolved it and each revision lists the requests it carries.
+//!
+//! A change log is saved as JSON next to the sheet set it refers to.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
+
+use super::set::SheetSet;
+use super::{SheetError, SheetResult};
+use crate::io::fields::REVISION_PROPERTY;
+
+/// Sheet field describing the sheet's revision
+pub const FIELD_REVISION_DESCRIPTION: &str = "REVISION_DESCRIPTION";
+/// Sheet field listing the change requests the sheet's revision carries
+pub const FIELD_REVISION_CHANGES: &str = "REVISION_CHANGES";
+
+/// Review comment placed on a sheet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Markup {
+    /// Markup identifier
+    pub id: Uuid,
+    /// Sheet the markup is on
+    pub sheet: Uuid,
+    /// Reviewer
+    pub author: String,
+    /// Review comment
+    pub comment: String,
+    /// Clouded area in paper millimeters, `[min x, min y, max x, max y]`
+    pub region: Option<[f64; 4]>,
+    /// When the markup was made
+    pub created: DateTime<Utc>,
+}
+
+impl Markup {
+    /// Create a markup without a clouded area
+    pub fn new(sheet: Uuid, author: &str, comment: &str) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            sheet,
+            author: author.to_string(),
+            comment: comment.to_string(),
+            region: None,
+            created: Utc::now(),
+        }
+    }
+
+    /// Cloud an area of the sheet, corners in any order
+    pub fn with_region(mut self, (x1, y1): (f64, f64), (x2, y2): (f64, f64)) -> Self {
+        self.region = Some([x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)]);
+        self
+    }
+}
+
+/// Workflow state of a change request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChangeStatus {
+    /// Raised, not yet being worked on
+    Open,
+    /// Assigned and being worked on
+    InProgress,
+    /// Drawings changed, waiting for a revision and a check
+    Implemented,
+    /// Checked against the issued revision
+    Verified,
+}
+
+impl ChangeStatus {
+    /// Whether the workflow allows moving to `next`
+    ///
+    /// Requests move forward one state at a time. Work can be handed back
+    /// (in progress to open), a failed check sends an implemented request
+    /// back to in progress, and a verified request can be reopened.
+    pub fn can_move_to(self, next: ChangeStatus) -> bool {
+        use ChangeStatus::*;
+        matches!(
+            (self, next),
+            (Open, InProgress)
+                | (InProgress, Open)
+                | (InProgress, Implemented)
+                | (Implemented, InProgress)
+                | (Implemented, Verified)
+                | (Verified, Open)
+        )
+    }
+}
+
+impl fmt::Display for ChangeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChangeStatus::Open => "open",
+            ChangeStatus::InProgress => "in progress",
+            ChangeStatus::Implemented => "implemented",
+            ChangeStatus::Verified => "verified",
+        })
+    }
+}
+
+/// One step of a request's history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusChange {
+    /// State before
+    pub from: ChangeStatus,
+    /// State after
+    pub to: ChangeStatus,
+    /// Who moved the request
+    pub by: String,
+    /// When
+    pub at: DateTime<Utc>,
+}
+
+/// Sheet revision that resolved a change request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevisionLink {
+    /// Revised sheet
+    pub sheet: Uuid,
+    /// Revision code, e.g. `C`
+    pub revision: String,
+    /// When the revision was issued
+    pub issued: DateTime<Utc>,
+}
+
+/// Group of markups handled as one change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeRequest {
+    /// Request identifier
+    pub id: Uuid,
+    /// Request number, e.g. `CR-004`
+    pub number: String,
+    /// Short title
+    pub title: String,
+    /// What has to change
+    pub description: String,
+    /// Person working on the request
+    pub assignee: Option<String>,
+    /// Workflow state
+    pub status: ChangeStatus,
+    /// Markups the request answers
+    pub markups: Vec<Uuid>,
+    /// Revisions that resolved the request, in issue order
+    pub revisions: Vec<RevisionLink>,
+    /// State changes, oldest first
+    pub history: Vec<StatusChange>,
+    /// When the request was raised
+    pub created: DateTime<Utc>,
+}
+
+impl ChangeRequest {
+    /// Whether the request has a revision of the sheet
+    pub fn is_resolved_on(&self, sheet: Uuid) -> bool {
+        self.revisions.iter().any(|r| r.sheet == sheet)
+    }
+}
+
+/// Markups and change requests of one sheet set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeLog {
+    /// Prefix of request numbers
+    pub prefix: String,
+    /// Digits of request numbers, zero padded
+    pub digits: usize,
+    /// Every markup, grouped or not
+    pub markups: Vec<Markup>,
+    /// Change requests in the order raised
+    pub requests: Vec<ChangeRequest>,
+}
+
+impl Default for ChangeLog {
+    fn default() -> Self {
+        Self {
+            prefix: "CR-".to_string(),
+            digits: 3,
+            markups: Vec::new(),
+            requests: Vec::new(),
+        }
+    }
+}
+
+impl ChangeLog {
+    /// Create an empty log numbering requests `CR-001`, `CR-002`, ...
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a log from JSON
+    pub fn load(path: impl AsRef<Path>) -> SheetResult<Self> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Save the log as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> SheetResult<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Add a markup and return its id
+    pub fn add_markup(&mut self, markup: Markup) -> Uuid {
+        let id = markup.id;
+        self.markups.push(markup);
+        id
+    }
+
+    /// Get a markup by id
+    pub fn markup(&self, id: Uuid) -> Option<&Markup> {
+        self.markups.iter().find(|m| m.id == id)
+    }
+
+    /// Remove a markup that no request refers to
+    pub fn remove_markup(&mut self, id: Uuid) -> SheetResult<Markup> {
+        if let Some(request) = self.request_for_markup(id) {
+            return Err(SheetError::Workflow(format!(
+                "markup belongs to {}",
+                request.number
+            )));
+        }
+        let index = self
+            .markups
+            .iter()
+            .position(|m| m.id == id)
+            .ok_or_else(|| SheetError::NotFound(format!("markup {}", id)))?;
+        Ok(self.markups.remove(index))
+    }
+
+    /// Markups not yet grouped into a request
+    pub fn ungrouped_markups(&self) -> Vec<&Markup> {
+        self.markups
+            .iter()
+            .filter(|m| self.request_for_markup(m.id).is_none())
+            .collect()
+    }
+
+    /// Group markups into a new open request and return its id
+    ///
+    /// Each markup can belong to one request only.
+    pub fn create_request(&mut self, title: &str, markups: &[Uuid]) -> SheetResult<Uuid> {
+        if markups.is_empty() {
+            return Err(SheetError::Workflow(
+                "a change request needs at least one markup".to_string(),
+            ));
+        }
+        for &id in markups {
+            self.check_groupable(id)?;
+        }
+
+        let number = self.requests.len() + 1;
+        let request = ChangeRequest {
+            id: Uuid::new_v4(),
+            number: format!("{}{:0width$}", self.prefix, number, width = self.digits),
+            title: title.to_string(),
+            description: String::new(),
+            assignee: None,
+            status: ChangeStatus::Open,
+            markups: markups.iter().copied().collect::<BTreeSet<_>>().into_iter().collect(),
+            revisions: Vec::new(),
+            history: Vec::new(),
+            created: Utc::now(),
+        };
+        let id = request.id;
+        self.requests.push(request);
+        Ok(id)
+    }
+
+    /// Add a markup to a request that is not yet implemented
+    pub fn add_to_request(&mut self, request: Uuid, markup: Uuid) -> SheetResult<()> {
+        self.check_groupable(markup)?;
+        let request = self.request_mut(request)?;
+        if !matches!(request.status, ChangeStatus::Open | ChangeStatus::InProgress) {
+            return Err(SheetError::Workflow(format!(
+                "{} is {}",
+                request.number, request.status
+            )));
+        }
+        request.markups.push(markup);
+        Ok(())
+    }
+
+    /// Get a request by id
+    pub fn request(&self, id: Uuid) -> Option<&ChangeRequest> {
+        self.requests.iter().find(|r| r.id == id)
+    }
+
+    /// Get a request by number, ignoring case
+    pub fn request_by_number(&self, number: &str) -> Option<&ChangeRequest> {
+        self.requests.iter().find(|r| r.number.eq_ignore_ascii_case(number))
+    }
+
+    /// The request a markup belongs to
+    pub fn request_for_markup(&self, markup: Uuid) -> Option<&ChangeRequest> {
+        self.requests.iter().find(|r| r.markups.contains(&markup))
+    }
+
+    /// Requests with markups on a sheet
+    pub fn requests_for_sheet(&self, sheet: Uuid) -> Vec<&ChangeRequest> {
+        self.requests
+            .iter()
+            .filter(|r| self.sheets_of(r).contains(&sheet))
+            .collect()
+    }
+
+    /// Requests assigned to a person
+    pub fn assigned_to(&self, assignee: &str) -> Vec<&ChangeRequest> {
+        self.requests
+            .iter()
+            .filter(|r| r.assignee.as_deref() == Some(assignee))
+            .collect()
+    }
+
+    /// Requests in a state
+    pub fn with_status(&self, status: ChangeStatus) -> Vec<&ChangeRequest> {
+        self.requests.iter().filter(|r| r.status == status).collect()
+    }
+
+    /// Sheets a request's markups are on
+    pub fn sheets_of(&self, request: &ChangeRequest) -> BTreeSet<Uuid> {
+        request
+            .markups
+            .iter()
+            .filter_map(|&id| self.markup(id))
+            .map(|m| m.sheet)
+            .collect()
+    }
+
+    /// Assign a request, or unassign it with `None`
+    ///
+    /// A request in progress must keep an assignee.
+    pub fn assign(&mut self, request: Uuid, assignee: Option<&str>) -> SheetResult<()> {
+        let request = self.request_mut(request)?;
+        if assignee.is_none() && request.status == ChangeStatus::InProgress {
+            return Err(SheetError::Workflow(format!(
+                "{} is in progress and needs an assignee",
+                request.number
+            )));
+        }
+        request.assignee = assignee.map(str::to_string);
+        Ok(())
+    }
+
+    /// Move a request to another state, recording who did it
+    ///
+    /// Work can only start on an assigned request, and a request is only
+    /// verified once every sheet it touches has a revision resolving it.
+    pub fn set_status(&mut self, request: Uuid, status: ChangeStatus, by: &str) -> SheetResult<()> {
+        let unresolved = self
+            .request(request)
+            .map(|r| self.sheets_of(r).into_iter().filter(|&s| !r.is_resolved_on(s)).count())
+            .unwrap_or_default();
+        let request = self.request_mut(request)?;
+        if !request.status.can_move_to(status) {
+            return Err(SheetError::Workflow(format!(
+                "{} cannot move from {} to {}",
+                request.number, request.status, status
+            )));
+        }
+        if status == ChangeStatus::InProgress && request.assignee.is_none() {
+            return Err(SheetError::Workflow(format!(
+                "{} needs an assignee before work starts",
+                request.number
+            )));
+        }
+        if status == ChangeStatus::Verified && unresolved > 0 {
+            return Err(SheetError::Workflow(format!(
+                "{} has {} sheet(s) without a resolving revision",
+                request.number, unresolved
+            )));
+        }
+
+        request.history.push(StatusChange {
+            from: request.status,
+            to: status,
+            by: by.to_string(),
+            at: Utc::now(),
+        });
+        request.status = status;
+        Ok(())
+    }
+
+    /// Issue a sheet revision and link it to the requests it resolves
+    ///
+    /// Writes the revision code, description, and the numbers of the
+    /// resolved requests into the sheet's fields, where title blocks and
+    /// reports pick them up. Every implemented request with markups on the
+    /// sheet and no earlier revision of it is resolved by this one; their
+    /// ids are returned.
+    pub fn issue_revision(
+        &mut self,
+        set: &mut SheetSet,
+        sheet: Uuid,
+        revision: &str,
+        description: &str,
+    ) -> SheetResult<Vec<Uuid>> {
+        let sheet_ref = set
+            .sheet_mut(sheet)
+            .ok_or_else(|| SheetError::NotFound(format!("sheet {}", sheet)))?;
+
+        let resolved: Vec<Uuid> = self
+            .requests
+            .iter()
+            .filter(|r| r.status == ChangeStatus::Implemented && !r.is_resolved_on(sheet))
+            .filter(|r| self.sheets_of(r).contains(&sheet))
+            .map(|r| r.id)
+            .collect();
+
+        let issued = Utc::now();
+        let mut numbers = Vec::with_capacity(resolved.len());
+        for request in self.requests.iter_mut().filter(|r| resolved.contains(&r.id)) {
+            request.revisions.push(RevisionLink {
+                sheet,
+                revision: revision.to_string(),
+                issued,
+            });
+            numbers.push(request.number.clone());
+        }
+
+        for (field, value) in [
+            (REVISION_PROPERTY, revision.to_string()),
+            (FIELD_REVISION_DESCRIPTION, description.to_string()),
+            (FIELD_REVISION_CHANGES, numbers.join(", ")),
+        ] {
+            sheet_ref.fields.insert(field.to_string(), value);
+        }
+        Ok(resolved)
+    }
+
+    /// Check that a markup exists and is not grouped yet
+    fn check_groupable(&self, markup: Uuid) -> SheetResult<()> {
+        if self.markup(markup).is_none() {
+            return Err(SheetError::NotFound(format!("markup {}", markup)));
+        }
+        match self.request_for_markup(markup) {
+            Some(request) => Err(SheetError::Workflow(format!(
+                "markup already belongs to {}",
+                request.number
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn request_mut(&mut self, id: Uuid) -> SheetResult<&mut ChangeRequest> {
+        self.requests
+            .iter_mut()
+            .find(|r| r.id == id)
+            .ok_or_else(|| SheetError::NotFound(format!("change request {}", id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheets::set::Sheet;
+
+    fn sample() -> (SheetSet, Uuid, Uuid, ChangeLog) {
+        let mut set = SheetSet::new("Clinic");
+        let arch = set.add_subset("Architectural", "A-");
+        let plan = arch.add_sheet(Sheet::new("Ground Floor Plan", "plans.cdy"));
+        let section = arch.add_sheet(Sheet::new("Sections", "sections.cdy"));
+        (set, plan, section, ChangeLog::new())
+    }
+
+    #[test]
+    fn test_workflow_and_revision_links() {
+        let (mut set, plan, section, mut log) = sample();
+        let door = log.add_markup(
+            Markup::new(plan, "reviewer", "Door swing clashes with column")
+                .with_region((120.0, 80.0), (100.0, 60.0)),
+        );
+        let stair = log.add_markup(Markup::new(section, "reviewer", "Stair headroom"));
+        let note = log.add_markup(Markup::new(plan, "reviewer", "Typo in room name"));
+        assert_eq!(log.markup(door).unwrap().region, Some([100.0, 60.0, 120.0, 80.0]));
+
+        let cr = log.create_request("Stair core changes", &[door, stair]).unwrap();
+        assert_eq!(log.request(cr).unwrap().number, "CR-001");
+        assert_eq!(log.ungrouped_markups().len(), 1);
+        assert!(matches!(
+            log.create_request("Again", &[door]),
+            Err(SheetError::Workflow(_))
+        ));
+
+        // Work needs an assignee, and states cannot be skipped
+        assert!(log.set_status(cr, ChangeStatus::InProgress, "lead").is_err());
+        log.assign(cr, Some("drafter")).unwrap();
+        assert!(log.set_status(cr, ChangeStatus::Verified, "lead").is_err());
+        log.set_status(cr, ChangeStatus::InProgress, "drafter").unwrap();
+        log.set_status(cr, ChangeStatus::Implemented, "drafter").unwrap();
+        assert_eq!(log.assigned_to("drafter").len(), 1);
+
+        // Revising one sheet resolves it there only
+        let resolved = log.issue_revision(&mut set, plan, "B", "Door moved").unwrap();
+        assert_eq!(resolved, vec![cr]);
+        let fields = &set.sheet(plan).unwrap().fields;
+        assert_eq!(fields[REVISION_PROPERTY], "B");
+        assert_eq!(fields[FIELD_REVISION_CHANGES], "CR-001");
+        assert!(matches!(
+            log.set_status(cr, ChangeStatus::Verified, "checker"),
+            Err(SheetError::Workflow(_))
+        ));
+
+        log.issue_revision(&mut set, section, "C", "Stair lowered").unwrap();
+        log.set_status(cr, ChangeStatus::Verified, "checker").unwrap();
+        let request = log.request(cr).unwrap();
+        assert_eq!(request.revisions.len(), 2);
+        assert_eq!(request.revisions[1].revision, "C");
+        assert_eq!(request.history.len(), 3);
+
+        // Later revisions do not relink resolved requests
+        assert!(log.issue_revision(&mut set, plan, "C", "Room names").unwrap().is_empty());
+        assert_eq!(set.sheet(plan).unwrap().fields[FIELD_REVISION_CHANGES], "");
+        assert_eq!(log.requests_for_sheet(plan).len(), 1);
+        assert!(log.remove_markup(door).is_err());
+        assert!(log.remove_markup(note).is_ok());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let (_, plan, _, mut log) = sample();
+        let markup = log.add_markup(Markup::new(plan, "reviewer", "Add grid lines"));
+        let cr = log.create_request("Grid", &[markup]).unwrap();
+        log.assign(cr, Some("drafter")).unwrap();
+
+        let path = std::env::temp_dir().join(format!("caddy-changes-{}.json", Uuid::new_v4()));
+        log.save(&path).unwrap();
+        let loaded = ChangeLog::load(&path).unwrap();
+        fs::remove_file(&path).ok();
+        assert_eq!(loaded, log);
+        assert_eq!(loaded.request_by_number("cr-001").unwrap().id, cr);
+    }
+}
@@ -0,0 +1,115 @@
+//! DWF output
+//!
+//! Writes one plotted page as a classic single-sheet ASCII DWF: line,
+//! polyline, and text opcodes in integer logical units of 0.01 mm. Multi-sheet
+//! publishing writes one file per sheet.
+
+use std::fmt::Write as _;
+
+use super::plot::PlotPage;
+
+/// Logical units per millimeter
+const UNITS_PER_MM: f64 = 100.0;
+
+/// Write a page as an ASCII DWF stream
+pub fn write_dwf(page: &PlotPage) -> Vec<u8> {
+    let unit = |v: f64| (v * UNITS_PER_MM).round() as i64;
+    let mut s = String::new();
+    s.push_str("(DWF V06.00)\n");
+    let _ = writeln!(s, "(Description \"{}\")", escape(&page.name));
+    let _ = writeln!(s, "(LineWeight {})", unit(page.line_width).max(1));
+
+    for path in &page.paths {
+        let mut points: Vec<(i64, i64)> = path
+            .points
+            .iter()
+            .map(|&(x, y)| (unit(x), unit(y)))
+            .collect();
+        if path.closed {
+            points.push(points[0]);
+        }
+        if points.len() == 2 {
+            let _ = writeln!(
+                s,
+                "L {},{} {},{}",
+                points[0].0, points[0].1, points[1].0, points[1].1
+            );
+        } else {
+            let _ = write!(s, "P {}", points.len());
+            for (x, y) in points {
+                let _ = write!(s, " {},{}", x, y);
+            }
+            s.push('\n');
+        }
+    }
+
+    for text in page.texts.iter().filter(|t| !t.text.is_empty()) {
+        let (x, y) = (unit(text.position.0), unit(text.position.1));
+        // Rotation in 1/65536ths of a turn, as the format expects
+        let turns = text.rotation.rem_euclid(std::f64::consts::TAU) / std::f64::consts::TAU;
+        let _ = writeln!(
+            s,
+            "(Font (Name \"Helvetica\") (Height {}) (Rotation {}))",
+            unit(text.height),
+            (turns * 65536.0).round() as i64 % 65536
+        );
+        let _ = writeln!(s, "(DrawText {},{} \"{}\")", x, y, escape(&text.text));
+    }
+
+    s.push_str("(EndOfDWF)\n");
+    s.into_bytes()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::Color;
+    use crate::sheets::plot::{PlotPath, PlotText};
+
+    #[test]
+    fn test_opcodes() {
+        let page = PlotPage {
+            name: "A-101 \"Plan\"".to_string(),
+            width: 297.0,
+            height: 210.0,
+            line_width: 0.35,
+            scale: 1.0,
+            origin: (0.0, 0.0),
+            paths: vec![
+                PlotPath {
+                    points: vec![(0.0, 0.0), (10.0, 5.5)],
+                    closed: false,
+                    color: Color::black(),
+                    weight: None,
+                },
+                PlotPath {
+                    points: vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)],
+                    closed: true,
+                    color: Color::black(),
+                    weight: None,
+                },
+            ],
+            texts: vec![PlotText {
+                position: (20.0, 30.0),
+                height: 2.5,
+                rotation: std::f64::consts::FRAC_PI_2,
+                text: "Ground floor".to_string(),
+            }],
+            images: Vec::new(),
+        };
+        let dwf = String::from_utf8(write_dwf(&page)).unwrap();
+        let lines: Vec<&str> = dwf.lines().collect();
+        assert_eq!(lines[0], "(DWF V06.00)");
+        assert_eq!(lines[1], "(Description \"A-101 \\\"Plan\\\"\")");
+        assert!(lines.contains(&"(LineWeight 35)"));
+        assert!(lines.contains(&"L 0,0 1000,550"));
+        assert!(lines.contains(&"P 4 0,0 1000,0 1000,1000 0,0"));
+        assert!(lines.contains(&"(Font (Name \"Helvetica\") (Height 250) (Rotation 16384))"));
+        assert!(lines.contains(&"(DrawText 2000,3000 \"Ground floor\")"));
+        assert_eq!(*lines.last().unwrap(), "(EndOfDWF)");
+    }
+}
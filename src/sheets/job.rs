@@ -0,0 +1,311 @@
+//! Scheduled publishing and reports
+//!
+//! Publishing a large set can take minutes, so it runs as a scheduler job:
+//! [`publish_job`] builds a job whose payload is a [`PublishRequest`], and a
+//! [`PublishExecutor`] registered with the
+//! [`JobScheduler`](crate::scheduling::JobScheduler) runs it on a blocking
+//! thread. Project documentation reports work the same way through
+//! [`report_job`] and [`ReportExecutor`]; [`ProjectReports`] keeps the
+//! projects the API can generate and schedule reports for.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use super::publish::PublishRequest;
+use super::report::{ReportFormat, ReportRequest, ReportTemplate};
+use super::{SheetError, SheetResult};
+use crate::scheduling::{
+    Job, JobExecutor, JobSchedule, JobScheduler, JobStatus, SchedulerError, SchedulerResult,
+};
+
+/// Job type handled by [`PublishExecutor`]
+pub const PUBLISH_JOB_TYPE: &str = "sheet-set-publish";
+
+/// Job type handled by [`ReportExecutor`]
+pub const REPORT_JOB_TYPE: &str = "project-report";
+
+/// Build a publish job for the scheduler
+pub fn publish_job(request: &PublishRequest, schedule: JobSchedule) -> SheetResult<Job> {
+    let name = request
+        .set_path
+        .file_stem()
+        .map(|s| format!("Publish {}", s.to_string_lossy()))
+        .unwrap_or_else(|| "Publish sheet set".to_string());
+    let mut job = Job::new(name, PUBLISH_JOB_TYPE.to_string(), schedule);
+    job.payload = serde_json::to_value(request)?;
+    job.tags.insert(
+        "format".to_string(),
+        request.options.format.extension().to_string(),
+    );
+    Ok(job)
+}
+
+/// Executes publish jobs
+///
+/// A run where any sheet could not be published fails the job, so the
+/// scheduler's retry and error reporting apply; the sheets that did plot
+/// are still written.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PublishExecutor;
+
+#[async_trait]
+impl JobExecutor for PublishExecutor {
+    async fn execute(&self, job: &Job) -> SchedulerResult<()> {
+        let request: PublishRequest = serde_json::from_value(job.payload.clone())?;
+        let report = tokio::task::spawn_blocking(move || request.run())
+            .await
+            .map_err(|e| SchedulerError::ExecutionError(e.to_string()))?
+            .map_err(|e| SchedulerError::ExecutionError(e.to_string()))?;
+
+        log::info!(
+            "Job {} published {} file(s) for {} sheet(s)",
+            job.id,
+            report.files.len(),
+            report.sheets.len()
+        );
+
+        let failures: Vec<String> = report
+            .failures()
+            .map(|s| format!("{} ({})", s.number, s.error.as_deref().unwrap_or_default()))
+            .collect();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(SchedulerError::ExecutionError(format!(
+                "{} of {} sheets not published: {}",
+                failures.len(),
+                report.sheets.len(),
+                failures.join(", ")
+            )))
+        }
+    }
+
+    fn job_type(&self) -> &str {
+        PUBLISH_JOB_TYPE
+    }
+}
+
+/// Build a report job for the scheduler, tagged with its project
+pub fn report_job(
+    request: &ReportRequest,
+    project: &str,
+    schedule: JobSchedule,
+) -> SheetResult<Job> {
+    let name = format!("{} report for {}", request.template.name, project);
+    let mut job = Job::new(name, REPORT_JOB_TYPE.to_string(), schedule);
+    job.payload = serde_json::to_value(request)?;
+    job.tags.insert("project".to_string(), project.to_string());
+    job.tags
+        .insert("template".to_string(), request.template.name.clone());
+    job.tags
+        .insert("format".to_string(), request.format.extension().to_string());
+    Ok(job)
+}
+
+/// Executes report jobs
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ReportExecutor;
+
+#[async_trait]
+impl JobExecutor for ReportExecutor {
+    async fn execute(&self, job: &Job) -> SchedulerResult<()> {
+        let request: ReportRequest = serde_json::from_value(job.payload.clone())?;
+        let path = tokio::task::spawn_blocking(move || request.run())
+            .await
+            .map_err(|e| SchedulerError::ExecutionError(e.to_string()))?
+            .map_err(|e| SchedulerError::ExecutionError(e.to_string()))?;
+        log::info!("Job {} wrote report {}", job.id, path.display());
+        Ok(())
+    }
+
+    fn job_type(&self) -> &str {
+        REPORT_JOB_TYPE
+    }
+}
+
+/// Projects whose documentation can be generated and scheduled on demand
+///
+/// Each project is a sheet set file; reports go to a folder per project
+/// under the output folder. Register a [`ReportExecutor`] with the same
+/// scheduler so scheduled reports run.
+pub struct ProjectReports {
+    projects: RwLock<HashMap<String, PathBuf>>,
+    output_dir: PathBuf,
+    scheduler: Arc<JobScheduler>,
+}
+
+impl ProjectReports {
+    /// Create an empty registry writing scheduled reports under `output_dir`
+    pub fn new(scheduler: Arc<JobScheduler>, output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            projects: RwLock::new(HashMap::new()),
+            output_dir: output_dir.into(),
+            scheduler,
+        }
+    }
+
+    /// Add or move a project
+    pub async fn register(&self, project: &str, set_path: impl Into<PathBuf>) {
+        self.projects
+            .write()
+            .await
+            .insert(project.to_string(), set_path.into());
+    }
+
+    /// Request for a report on a registered project
+    pub async fn request(
+        &self,
+        project: &str,
+        template: ReportTemplate,
+        format: ReportFormat,
+    ) -> SheetResult<ReportRequest> {
+        template.validate()?;
+        let set_path = self
+            .projects
+            .read()
+            .await
+            .get(project)
+            .cloned()
+            .ok_or_else(|| SheetError::NotFound(format!("project '{}'", project)))?;
+        Ok(ReportRequest::new(
+            set_path,
+            template,
+            format,
+            self.output_dir.join(project),
+        ))
+    }
+
+    /// Render a report now, on a blocking thread
+    pub async fn generate(&self, request: ReportRequest) -> SheetResult<Vec<u8>> {
+        tokio::task::spawn_blocking(move || request.generate())
+            .await
+            .map_err(|e| SheetError::Io(std::io::Error::other(e)))?
+    }
+
+    /// Schedule a report for a project
+    pub async fn schedule(
+        &self,
+        project: &str,
+        request: &ReportRequest,
+        schedule: JobSchedule,
+    ) -> SchedulerResult<Job> {
+        let job = report_job(request, project, schedule)
+            .map_err(|e| SchedulerError::ExecutionError(e.to_string()))?;
+        let id = self.scheduler.schedule_job(job).await?;
+        self.scheduler
+            .get_job(&id)
+            .await
+            .ok_or(SchedulerError::JobNotFound(id))
+    }
+
+    /// A project's report jobs that have not been cancelled
+    pub async fn schedules(&self, project: &str) -> Vec<Job> {
+        let mut jobs: Vec<Job> = self
+            .scheduler
+            .list_jobs()
+            .await
+            .into_iter()
+            .filter(|job| is_report_of(job, project) && job.status != JobStatus::Cancelled)
+            .collect();
+        jobs.sort_by_key(|job| job.created_at);
+        jobs
+    }
+
+    /// Cancel one of a project's report jobs
+    pub async fn cancel(&self, project: &str, job_id: &str) -> SchedulerResult<()> {
+        match self.scheduler.get_job(job_id).await {
+            Some(job) if is_report_of(&job, project) => self.scheduler.cancel_job(job_id).await,
+            _ => Err(SchedulerError::JobNotFound(job_id.to_string())),
+        }
+    }
+}
+
+fn is_report_of(job: &Job, project: &str) -> bool {
+    job.job_type == REPORT_JOB_TYPE && job.tags.get("project").map(String::as_str) == Some(project)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheets::publish::PublishOptions;
+    use crate::sheets::report::ReportTemplate;
+    use crate::sheets::set::{Sheet, SheetSet};
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_job_payload_roundtrip() {
+        let request = PublishRequest {
+            set_path: PathBuf::from("/projects/depot/depot.sheets.json"),
+            options: PublishOptions::dwf("/projects/depot/out"),
+        };
+        let job = publish_job(&request, JobSchedule::Once(chrono::Utc::now())).unwrap();
+        assert_eq!(job.job_type, PUBLISH_JOB_TYPE);
+        assert_eq!(job.name, "Publish depot.sheets");
+        assert_eq!(job.tags["format"], "dwf");
+        let back: PublishRequest = serde_json::from_value(job.payload).unwrap();
+        assert_eq!(back, request);
+    }
+
+    #[tokio::test]
+    async fn test_executor_reports_failed_sheets() {
+        let dir = std::env::temp_dir().join(format!("publish-job-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut set = SheetSet::new("Depot");
+        set.add_subset("Architectural", "A-")
+            .add_sheet(Sheet::new("Plan", "missing.cdy"));
+        let set_path = dir.join("depot.json");
+        set.save(&set_path).unwrap();
+
+        let request = PublishRequest {
+            set_path,
+            options: PublishOptions::pdf(dir.join("out")),
+        };
+        let job = publish_job(&request, JobSchedule::Once(chrono::Utc::now())).unwrap();
+        let err = PublishExecutor.execute(&job).await.unwrap_err();
+        assert!(err.to_string().contains("A-001"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_report_job() {
+        let dir = std::env::temp_dir().join(format!("report-job-{}", Uuid::new_v4()));
+        let request = ReportRequest::new(
+            dir.join("depot.sheets.json"),
+            ReportTemplate::project_documentation(),
+            ReportFormat::Html,
+            dir.join("reports"),
+        );
+        let job = report_job(
+            &request,
+            "depot",
+            JobSchedule::Cron("0 0 6 * * Mon".to_string()),
+        )
+        .unwrap();
+        assert_eq!(job.job_type, REPORT_JOB_TYPE);
+        assert_eq!(job.name, "project-documentation report for depot");
+        assert_eq!(job.tags["format"], "html");
+        assert!(is_report_of(&job, "depot"));
+        assert!(!is_report_of(&job, "clinic"));
+        let back: ReportRequest = serde_json::from_value(job.payload.clone()).unwrap();
+        assert_eq!(back, request);
+
+        // No sheet set file yet
+        assert!(ReportExecutor.execute(&job).await.is_err());
+
+        std::fs::create_dir_all(&dir).unwrap();
+        SheetSet::new("Depot")
+            .save(dir.join("depot.sheets.json"))
+            .unwrap();
+        ReportExecutor.execute(&job).await.unwrap();
+        let html = std::fs::read_to_string(request.output_path()).unwrap();
+        assert!(html.contains("<h1>Depot</h1>"));
+        assert!(html.contains("The set has no sheets."));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,126 @@
+//! # CADDY Sheet Sets
+//!
+//! Organizes plotted sheets from many drawings into one deliverable:
+//!
+//! - **Sheet sets**: subsets (disciplines) of sheets, each pointing at a
+//!   drawing and the view to plot, saved as JSON next to the drawings
+//! - **Numbering**: automatic sheet numbers from subset prefixes, with
+//!   manual overrides and duplicate detection
+//! - **Title blocks**: set, subset, and sheet fields written into title
+//!   block attributes and `{{FIELD}}` text placeholders
+//! - **Index sheet**: a generated drawing list published ahead of the set
+//! - **Publishing**: batch plotting to a combined PDF or per-sheet DWF,
+//!   optionally as a scheduled job
+//! - **Redaction**: confidential layers stripped from published sheets, or
+//!   rasterized in PDF output
+//! - **Reports**: scripted project documentation (drawing list, revision
+//!   history, standards results, sheet images) as PDF, HTML, or Markdown,
+//!   optionally as a scheduled job
+//! - **Change requests**: review markups grouped into assigned requests,
+//!   tracked from open to verified and linked to the sheet revisions that
+//!   resolved them
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use caddy::sheets::{IndexSheet, PublishOptions, Publisher, Sheet, SheetSet};
+//! use std::path::Path;
+//!
+//! let mut set = SheetSet::new("Riverside Clinic");
+//! set.set_field("PROJECT", "Riverside Clinic");
+//! set.title_block = Some("TITLE_A1".to_string());
+//! set.index = Some(IndexSheet::default());
+//!
+//! let arch = set.add_subset("Architectural", "A-");
+//! arch.add_sheet(Sheet::new("Ground Floor Plan", "plans.cdy").with_view("GF"));
+//! arch.add_sheet(Sheet::new("First Floor Plan", "plans.cdy").with_view("FF"));
+//! set.save("clinic.sheets.json").unwrap();
+//!
+//! let report = Publisher::new(PublishOptions::pdf("publish"))
+//!     .publish(&set, Path::new("."))
+//!     .unwrap();
+//! println!("{} files written", report.files.len());
+//! ```
+
+pub mod changes;
+pub mod dwf;
+#[cfg(feature = "native")]
+pub mod job;
+pub mod pdf;
+pub mod plot;
+pub mod publish;
+#[cfg(feature = "native")]
+pub mod report;
+pub mod set;
+
+use std::path::PathBuf;
+use thiserror::Error;
+
+pub use changes::{ChangeLog, ChangeRequest, ChangeStatus, Markup, RevisionLink};
+pub use dwf::write_dwf;
+#[cfg(feature = "native")]
+pub use job::{
+    publish_job, report_job, ProjectReports, PublishExecutor, ReportExecutor, PUBLISH_JOB_TYPE,
+    REPORT_JOB_TYPE,
+};
+pub use pdf::write_pdf;
+pub use plot::{
+    plot_document, plot_document_with_fields, plot_redacted, Orientation, PlotArea, PlotImage, PlotPage, PlotPath, PlotScale,
+    PlotSettings, PlotText,
+};
+pub use publish::{
+    plot_set, plot_set_redacted, PublishFormat, PublishOptions, PublishReport, PublishRequest,
+    Publisher, SheetOutcome,
+};
+#[cfg(feature = "native")]
+pub use report::{
+    CollectOptions, ReportBlock, ReportColumn, ReportData, ReportFormat, ReportImage,
+    ReportRequest, ReportRow, ReportTemplate,
+};
+pub use set::{
+    fill_title_block, IndexSheet, NumberedSheet, Sheet, SheetNumbering, SheetSet, SheetSubset,
+};
+
+/// Sheet set errors
+#[derive(Debug, Error)]
+pub enum SheetError {
+    /// Sheet, subset, or view does not exist
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    /// Two sheets share a number
+    #[error("Duplicate sheet number: {0}")]
+    DuplicateNumber(String),
+
+    /// Drawing could not be loaded
+    #[error("Failed to load {}: {message}", path.display())]
+    Load {
+        /// Drawing path
+        path: PathBuf,
+        /// Loader error
+        message: String,
+    },
+
+    /// Drawing could not be plotted
+    #[error("Plot failed: {0}")]
+    Plot(String),
+
+    /// Change request workflow does not allow the operation
+    #[error("Change request: {0}")]
+    Workflow(String),
+
+    /// Report template is malformed
+    #[error("Invalid report template: {0}")]
+    Template(String),
+
+    /// File system error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Sheet set file is malformed
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// Result type for sheet set operations
+pub type SheetResult<T> = Result<T, SheetError>;
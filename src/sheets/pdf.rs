@@ -0,0 +1,312 @@
+//! Vector PDF output
+//!
+//! Writes plotted pages as a PDF 1.4 file: one page per sheet, strokes as
+//! path operators, text in Helvetica, and a bookmark per sheet so a
+//! published set can be navigated by sheet number. Page images are
+//! embedded as uncompressed RGB XObjects.
+
+use std::fmt::Write as _;
+
+use super::plot::{PlotImage, PlotPage, PlotText};
+
+/// PDF points per millimeter
+const PT_PER_MM: f64 = 72.0 / 25.4;
+
+/// Helvetica cap height as a fraction of the font size
+const CAP_HEIGHT: f64 = 0.718;
+
+/// Write pages into a single PDF document
+pub fn write_pdf(pages: &[PlotPage], title: &str) -> Vec<u8> {
+    // Object numbers: 1 catalog, 2 page tree, 3 font, 4 info, 5 outline
+    // root, then page, content stream, and bookmark for each page, then
+    // the images of every page in order
+    let page_obj = |i: usize| 6 + 3 * i;
+    let mut image_obj = Vec::with_capacity(pages.len());
+    let mut next = page_obj(pages.len());
+    for page in pages {
+        image_obj.push(next);
+        next += page.images.len();
+    }
+    let mut objects: Vec<String> = Vec::with_capacity(5 + 3 * pages.len());
+
+    objects.push("<< /Type /Catalog /Pages 2 0 R /Outlines 5 0 R /PageMode /UseOutlines >>".into());
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", page_obj(i)))
+        .collect();
+    objects.push(format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        kids.join(" "),
+        pages.len()
+    ));
+    objects.push(
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".into(),
+    );
+    objects.push(format!(
+        "<< /Title {} /Producer (CADDY {}) >>",
+        string(title),
+        env!("CARGO_PKG_VERSION")
+    ));
+    objects.push(if pages.is_empty() {
+        "<< /Type /Outlines /Count 0 >>".to_string()
+    } else {
+        format!(
+            "<< /Type /Outlines /First {} 0 R /Last {} 0 R /Count {} >>",
+            page_obj(0) + 2,
+            page_obj(pages.len() - 1) + 2,
+            pages.len()
+        )
+    });
+
+    for (i, page) in pages.iter().enumerate() {
+        let n = page_obj(i);
+        let mut resources = "/Font << /F1 3 0 R >>".to_string();
+        if !page.images.is_empty() {
+            resources.push_str(" /XObject <<");
+            for k in 0..page.images.len() {
+                let _ = write!(resources, " /Im{} {} 0 R", k, image_obj[i] + k);
+            }
+            resources.push_str(" >>");
+        }
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << {} >> /Contents {} 0 R >>",
+            num(page.width * PT_PER_MM),
+            num(page.height * PT_PER_MM),
+            resources,
+            n + 1
+        ));
+
+        let content = content_stream(page);
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}\nendstream",
+            content.len(),
+            content
+        ));
+
+        let mut bookmark = format!(
+            "<< /Title {} /Parent 5 0 R /Dest [{} 0 R /Fit]",
+            string(&page.name),
+            n
+        );
+        if i > 0 {
+            let _ = write!(bookmark, " /Prev {} 0 R", page_obj(i - 1) + 2);
+        }
+        if i + 1 < pages.len() {
+            let _ = write!(bookmark, " /Next {} 0 R", page_obj(i + 1) + 2);
+        }
+        bookmark.push_str(" >>");
+        objects.push(bookmark);
+    }
+
+    for image in pages.iter().flat_map(|p| &p.images) {
+        objects.push(image_object(image));
+    }
+
+    let mut out = Vec::new();
+    out.extend_from_slice(b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+
+    let xref = out.len();
+    let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(trailer, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        trailer,
+        "trailer\n<< /Size {} /Root 1 0 R /Info 4 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    );
+    out.extend_from_slice(trailer.as_bytes());
+    out
+}
+
+fn content_stream(page: &PlotPage) -> String {
+    let mut s = String::new();
+    for (k, image) in page.images.iter().enumerate() {
+        let _ = writeln!(
+            s,
+            "q {} 0 0 {} {} {} cm /Im{} Do Q",
+            num(image.size.0 * PT_PER_MM),
+            num(image.size.1 * PT_PER_MM),
+            num(image.position.0 * PT_PER_MM),
+            num(image.position.1 * PT_PER_MM),
+            k
+        );
+    }
+    let _ = writeln!(s, "{} w 1 J 1 j", num(page.line_width * PT_PER_MM));
+    for path in &page.paths {
+        for (i, &(x, y)) in path.points.iter().enumerate() {
+            let op = if i == 0 { "m" } else { "l" };
+            let _ = writeln!(s, "{} {} {}", num(x * PT_PER_MM), num(y * PT_PER_MM), op);
+        }
+        s.push_str(if path.closed { "h S\n" } else { "S\n" });
+    }
+    for text in page.texts.iter().filter(|t| !t.text.is_empty()) {
+        text_op(&mut s, text);
+    }
+    s
+}
+
+fn image_object(image: &PlotImage) -> String {
+    let mut data = String::with_capacity(image.rgb.len() * 2 + image.rgb.len() / 32 + 1);
+    for (i, byte) in image.rgb.iter().enumerate() {
+        if i > 0 && i % 64 == 0 {
+            data.push('\n');
+        }
+        let _ = write!(data, "{:02X}", byte);
+    }
+    data.push('>');
+    format!(
+        "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+         /BitsPerComponent 8 /Filter /ASCIIHexDecode /Length {} >>\nstream\n{}\nendstream",
+        image.width,
+        image.height,
+        data.len(),
+        data
+    )
+}
+
+fn text_op(s: &mut String, text: &PlotText) {
+    let size = text.height * PT_PER_MM / CAP_HEIGHT;
+    let (sin, cos) = text.rotation.sin_cos();
+    let _ = writeln!(
+        s,
+        "BT /F1 {} Tf {} {} {} {} {} {} Tm {} Tj ET",
+        num(size),
+        num(cos),
+        num(sin),
+        num(-sin),
+        num(cos),
+        num(text.position.0 * PT_PER_MM),
+        num(text.position.1 * PT_PER_MM),
+        string(&text.text)
+    );
+}
+
+/// Number with at most three decimals and no trailing zeros
+fn num(v: f64) -> String {
+    let s = format!("{:.3}", v);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    match s {
+        "-0" | "" => "0".to_string(),
+        _ => s.to_string(),
+    }
+}
+
+/// PDF literal string in WinAnsi encoding
+fn string(text: &str) -> String {
+    let mut s = String::with_capacity(text.len() + 2);
+    s.push('(');
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                s.push('\\');
+                s.push(c);
+            }
+            ' '..='~' => s.push(c),
+            // WinAnsi matches Latin-1 in this range
+            '\u{a0}'..='\u{ff}' => {
+                let _ = write!(s, "\\{:03o}", c as u32);
+            }
+            _ => s.push('?'),
+        }
+    }
+    s.push(')');
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::Color;
+    use crate::sheets::plot::PlotPath;
+
+    fn page(name: &str) -> PlotPage {
+        PlotPage {
+            name: name.to_string(),
+            width: 420.0,
+            height: 297.0,
+            line_width: 0.25,
+            scale: 1.0,
+            origin: (0.0, 0.0),
+            paths: vec![PlotPath {
+                points: vec![(10.0, 10.0), (410.0, 10.0), (410.0, 287.0)],
+                closed: true,
+                color: Color::black(),
+                weight: None,
+            }],
+            texts: vec![PlotText {
+                position: (20.0, 20.0),
+                height: 5.0,
+                rotation: 0.0,
+                text: "Plan (Level 1) 50°".to_string(),
+            }],
+            images: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_structure_and_xref() {
+        let pdf = write_pdf(&[page("A-001 Plan"), page("A-002 Sections")], "Set");
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/Count 2 >>"));
+        assert!(text.contains("/MediaBox [0 0 1190.551 841.89]"));
+        assert!(text.contains("(A-002 Sections)"));
+        assert!(text.contains("(Plan \\(Level 1\\) 50\\260) Tj"));
+        assert!(text.contains("h S"));
+
+        // Every xref entry points at its object header
+        let start: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        // Offsets are in bytes; the binary comment line rules out `text`
+        let xref = std::str::from_utf8(&pdf[start..]).unwrap();
+        for (i, line) in xref.lines().skip(3).take(11).enumerate() {
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(format!("{} 0 obj", i + 1).as_bytes()));
+        }
+    }
+
+    #[test]
+    fn test_embedded_image() {
+        let mut first = page("A-001 Plan");
+        first.images.push(PlotImage {
+            position: (10.0, 20.0),
+            size: (25.4, 25.4),
+            width: 2,
+            height: 1,
+            rgb: vec![255, 0, 0, 0, 0, 255],
+        });
+        let pdf = write_pdf(&[first, page("A-002 Sections")], "Set");
+        let text = String::from_utf8_lossy(&pdf);
+
+        // Images follow the 5 fixed objects and 3 per page
+        assert!(text.contains("/XObject << /Im0 12 0 R >>"));
+        assert_eq!(text.matches("/XObject <<").count(), 1);
+        assert!(text.contains("12 0 obj\n<< /Type /XObject /Subtype /Image /Width 2 /Height 1"));
+        assert!(text.contains("stream\nFF00000000FF>\nendstream"));
+        assert!(text.contains("q 72 0 0 72 28.346 56.693 cm /Im0 Do Q"));
+        assert!(text.contains("/Size 13 "));
+    }
+
+    #[test]
+    fn test_num() {
+        assert_eq!(num(1.0), "1");
+        assert_eq!(num(0.2500), "0.25");
+        assert_eq!(num(-0.0001), "0");
+        assert_eq!(num(841.889_7), "841.89");
+    }
+}
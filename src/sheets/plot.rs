@@ -0,0 +1,1012 @@
+//! Plotting drawings onto paper
+//!
+//! A drawing is flattened into paper-space paths and text in millimeters:
+//! curves are tessellated, block inserts expanded, and entities on hidden,
+//! frozen, or non-plottable layers dropped. Text fields are evaluated as
+//! of the plot. The page is what the PDF and DWF writers consume.
+
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+use super::{SheetError, SheetResult};
+use crate::geometry::{BSpline, NurbsCurve, Point2D};
+use crate::io::document::{
+    Color, Document, Entity, GeometryType, LineWeight, PaperSize, Polyline, Spline, Vec3,
+};
+use crate::io::fields::{evaluate, FieldContext};
+use crate::io::redaction::{RedactionAction, RedactionProfile};
+
+/// Segments used for a full circle
+const CIRCLE_SEGMENTS: usize = 72;
+
+/// Largest rasterized image edge in pixels; the resolution drops to fit
+const MAX_IMAGE_SIZE: u32 = 2048;
+
+/// Nested block inserts deeper than this are not drawn
+const MAX_BLOCK_DEPTH: usize = 16;
+
+/// Paper orientation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Orientation {
+    /// Long edge vertical
+    Portrait,
+    /// Long edge horizontal
+    Landscape,
+}
+
+/// Part of the drawing to plot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PlotArea {
+    /// Bounding box of everything plottable
+    Extents,
+    /// Named view of the drawing
+    View(String),
+    /// Explicit window in drawing units
+    Window {
+        /// Lower-left corner
+        min: (f64, f64),
+        /// Upper-right corner
+        max: (f64, f64),
+    },
+}
+
+/// Drawing-to-paper scale
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PlotScale {
+    /// Largest scale that fits the printable area
+    Fit,
+    /// Paper millimeters per drawing unit (1:100 in mm drawings is `0.01`)
+    Ratio(f64),
+}
+
+/// How a sheet is plotted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlotSettings {
+    /// Paper size
+    pub paper: PaperSize,
+    /// Paper orientation
+    pub orientation: Orientation,
+    /// Area to plot
+    pub area: PlotArea,
+    /// Scale
+    pub scale: PlotScale,
+    /// Margin on every side in millimeters
+    pub margin: f64,
+    /// Pen width in millimeters
+    pub line_width: f64,
+}
+
+impl Default for PlotSettings {
+    fn default() -> Self {
+        Self {
+            paper: PaperSize::A3,
+            orientation: Orientation::Landscape,
+            area: PlotArea::Extents,
+            scale: PlotScale::Fit,
+            margin: 10.0,
+            line_width: 0.25,
+        }
+    }
+}
+
+impl PlotSettings {
+    /// Default settings on the document's regional plot paper
+    pub fn for_document(doc: &Document) -> Self {
+        Self {
+            paper: doc.settings.regional.plot_paper,
+            ..Self::default()
+        }
+    }
+
+    /// Paper size in millimeters after orientation
+    pub fn paper_mm(&self) -> (f64, f64) {
+        let (w, h) = self.paper.dimensions_mm();
+        let (short, long) = (w.min(h), w.max(h));
+        match self.orientation {
+            Orientation::Portrait => (short, long),
+            Orientation::Landscape => (long, short),
+        }
+    }
+}
+
+/// Stroked path on the page
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotPath {
+    /// Points in paper millimeters
+    pub points: Vec<(f64, f64)>,
+    /// Close back to the first point
+    pub closed: bool,
+    /// Pen color
+    pub color: Color,
+    /// Lineweight in millimeters (`None` uses the page pen width)
+    pub weight: Option<f64>,
+}
+
+/// Text on the page
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotText {
+    /// Baseline start in paper millimeters
+    pub position: (f64, f64),
+    /// Cap height in millimeters
+    pub height: f64,
+    /// Rotation in radians
+    pub rotation: f64,
+    /// Content
+    pub text: String,
+}
+
+/// One plotted page
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotPage {
+    /// Page name (sheet number and title)
+    pub name: String,
+    /// Paper width in millimeters
+    pub width: f64,
+    /// Paper height in millimeters
+    pub height: f64,
+    /// Pen width in millimeters
+    pub line_width: f64,
+    /// Paper millimeters per drawing unit
+    pub scale: f64,
+    /// Drawing coordinates at the lower-left corner of the paper
+    pub origin: (f64, f64),
+    /// Stroked geometry
+    pub paths: Vec<PlotPath>,
+    /// Text
+    pub texts: Vec<PlotText>,
+    /// Bitmaps, drawn beneath the paths and text
+    pub images: Vec<PlotImage>,
+}
+
+/// Bitmap on the page
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotImage {
+    /// Lower-left corner in paper millimeters
+    pub position: (f64, f64),
+    /// Size in paper millimeters
+    pub size: (f64, f64),
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Packed RGB rows, top to bottom
+    pub rgb: Vec<u8>,
+}
+
+impl PlotPage {
+    /// Render the paths into a bitmap covering their extents
+    ///
+    /// Text is not rasterized. Returns `None` if there are no paths.
+    pub fn rasterize(&self, dpi: u32) -> Option<PlotImage> {
+        let pen = |p: &PlotPath| p.weight.unwrap_or(self.line_width).max(0.0);
+        let margin = self.paths.iter().map(pen).fold(0.0, f64::max);
+        let mut bounds: Option<((f64, f64), (f64, f64))> = None;
+        for (x, y) in self.paths.iter().flat_map(|p| p.points.iter().copied()) {
+            let b = bounds.get_or_insert(((x, y), (x, y)));
+            b.0 = (b.0 .0.min(x), b.0 .1.min(y));
+            b.1 = (b.1 .0.max(x), b.1 .1.max(y));
+        }
+        let (min, max) = bounds?;
+        let min = ((min.0 - margin).max(0.0), (min.1 - margin).max(0.0));
+        let max = ((max.0 + margin).min(self.width), (max.1 + margin).min(self.height));
+        if max.0 <= min.0 || max.1 <= min.1 {
+            return None;
+        }
+
+        let size = (max.0 - min.0, max.1 - min.1);
+        let mut pixel = 25.4 / dpi.max(1) as f64;
+        pixel = pixel.max(size.0.max(size.1) / MAX_IMAGE_SIZE as f64);
+        let width = ((size.0 / pixel).ceil() as u32).max(1);
+        let height = ((size.1 / pixel).ceil() as u32).max(1);
+        let mut rgb = vec![255u8; width as usize * height as usize * 3];
+
+        for path in &self.paths {
+            let half = (pen(path) / 2.0).max(pixel / 2.0);
+            let color = [path.color.r, path.color.g, path.color.b];
+            let mut segments: Vec<((f64, f64), (f64, f64))> =
+                path.points.windows(2).map(|w| (w[0], w[1])).collect();
+            if path.closed && path.points.len() > 2 {
+                segments.push((path.points[path.points.len() - 1], path.points[0]));
+            }
+            if path.points.len() == 1 {
+                segments.push((path.points[0], path.points[0]));
+            }
+            for (a, b) in segments {
+                // Pixel rows run top to bottom
+                let col = |x: f64| ((x - min.0) / pixel).floor().clamp(0.0, width as f64 - 1.0) as u32;
+                let row = |y: f64| ((max.1 - y) / pixel).floor().clamp(0.0, height as f64 - 1.0) as u32;
+                let (x0, x1) = (col(a.0.min(b.0) - half), col(a.0.max(b.0) + half));
+                let (y0, y1) = (row(a.1.max(b.1) + half), row(a.1.min(b.1) - half));
+                for j in y0..=y1 {
+                    for i in x0..=x1 {
+                        let p = (
+                            min.0 + (i as f64 + 0.5) * pixel,
+                            max.1 - (j as f64 + 0.5) * pixel,
+                        );
+                        if segment_distance(p, a, b) <= half {
+                            let at = (j as usize * width as usize + i as usize) * 3;
+                            rgb[at..at + 3].copy_from_slice(&color);
+                        }
+                    }
+                }
+            }
+        }
+
+        Some(PlotImage {
+            position: min,
+            size: (width as f64 * pixel, height as f64 * pixel),
+            width,
+            height,
+            rgb,
+        })
+    }
+}
+
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    ((p.0 - a.0 - t * dx).powi(2) + (p.1 - a.1 - t * dy).powi(2)).sqrt()
+}
+
+/// 2D similarity transform (scale, rotation, translation)
+#[derive(Debug, Clone, Copy)]
+struct Transform {
+    scale_x: f64,
+    scale_y: f64,
+    cos: f64,
+    sin: f64,
+    dx: f64,
+    dy: f64,
+}
+
+impl Transform {
+    fn identity() -> Self {
+        Self {
+            scale_x: 1.0,
+            scale_y: 1.0,
+            cos: 1.0,
+            sin: 0.0,
+            dx: 0.0,
+            dy: 0.0,
+        }
+    }
+
+    fn apply(&self, p: Vec3) -> (f64, f64) {
+        let (x, y) = (p.x * self.scale_x, p.y * self.scale_y);
+        (
+            x * self.cos - y * self.sin + self.dx,
+            x * self.sin + y * self.cos + self.dy,
+        )
+    }
+
+    /// `self` applied after `inner`
+    fn then(&self, inner: &Transform) -> Transform {
+        // Only uniform outer scales keep the result a similarity; inserts
+        // with non-uniform scale nested in rotated inserts are approximated
+        let origin = self.apply(Vec3::new(inner.dx, inner.dy, 0.0));
+        Transform {
+            scale_x: inner.scale_x * self.scale_x,
+            scale_y: inner.scale_y * self.scale_y,
+            cos: inner.cos * self.cos - inner.sin * self.sin,
+            sin: inner.sin * self.cos + inner.cos * self.sin,
+            dx: origin.0,
+            dy: origin.1,
+        }
+    }
+
+    fn rotation(&self) -> f64 {
+        self.sin.atan2(self.cos)
+    }
+
+    fn length_scale(&self) -> f64 {
+        (self.scale_x * self.scale_y).abs().sqrt()
+    }
+}
+
+/// Plot a drawing onto a page
+pub fn plot_document(doc: &Document, settings: &PlotSettings, name: &str) -> SheetResult<PlotPage> {
+    plot_document_with_fields(doc, settings, name, &FieldContext::plot())
+}
+
+/// Plot a drawing onto a page, evaluating text fields in `fields`
+pub fn plot_document_with_fields(
+    doc: &Document,
+    settings: &PlotSettings,
+    name: &str,
+    fields: &FieldContext,
+) -> SheetResult<PlotPage> {
+    let (width, height) = settings.paper_mm();
+    let printable = (
+        width - 2.0 * settings.margin,
+        height - 2.0 * settings.margin,
+    );
+    if printable.0 <= 0.0 || printable.1 <= 0.0 {
+        return Err(SheetError::Plot(format!(
+            "margin {} mm leaves no printable area",
+            settings.margin
+        )));
+    }
+
+    // Flatten in drawing units first, then map the plot area onto paper
+    let mut page = PlotPage {
+        name: name.to_string(),
+        width,
+        height,
+        line_width: settings.line_width,
+        scale: 1.0,
+        origin: (0.0, 0.0),
+        paths: Vec::new(),
+        texts: Vec::new(),
+        images: Vec::new(),
+    };
+    let page_pen = Pen {
+        color: Color::black(),
+        weight: None,
+    };
+    for entity in doc.entities.iter().filter(|e| plottable(e, doc)) {
+        let pen = Pen::resolve(entity, doc, page_pen);
+        flatten(&entity.geometry, doc, fields, &Transform::identity(), 0, pen, &mut page);
+    }
+
+    let (min, max) = match &settings.area {
+        PlotArea::Extents => extents(&page)
+            .ok_or_else(|| SheetError::Plot(format!("'{}' has nothing to plot", name)))?,
+        PlotArea::View(view) => {
+            let view = doc
+                .views
+                .get(view)
+                .ok_or_else(|| SheetError::NotFound(format!("view '{}'", view)))?;
+            (
+                (
+                    view.center.x - view.width / 2.0,
+                    view.center.y - view.height / 2.0,
+                ),
+                (
+                    view.center.x + view.width / 2.0,
+                    view.center.y + view.height / 2.0,
+                ),
+            )
+        }
+        PlotArea::Window { min, max } => (*min, *max),
+    };
+    let area = ((max.0 - min.0).max(1e-9), (max.1 - min.1).max(1e-9));
+
+    let scale = match settings.scale {
+        PlotScale::Fit => (printable.0 / area.0).min(printable.1 / area.1),
+        PlotScale::Ratio(r) if r > 0.0 => r,
+        PlotScale::Ratio(r) => {
+            return Err(SheetError::Plot(format!("invalid plot scale {}", r)));
+        }
+    };
+    // Center the plotted area in the printable region
+    let ox = settings.margin + (printable.0 - area.0 * scale) / 2.0;
+    let oy = settings.margin + (printable.1 - area.1 * scale) / 2.0;
+    let map = |(x, y): (f64, f64)| (ox + (x - min.0) * scale, oy + (y - min.1) * scale);
+    page.scale = scale;
+    page.origin = (min.0 - ox / scale, min.1 - oy / scale);
+
+    for path in &mut page.paths {
+        for p in &mut path.points {
+            *p = map(*p);
+        }
+    }
+    for text in &mut page.texts {
+        text.position = map(text.position);
+        text.height *= scale;
+    }
+    Ok(page)
+}
+
+/// Plot a drawing with content withheld by `profile`
+///
+/// The page is framed from the whole drawing, so removing content never
+/// shifts what remains. With [`RedactionAction::Rasterize`] the withheld
+/// linework comes back as a bitmap; withheld text is always dropped.
+pub fn plot_redacted(
+    doc: &Document,
+    settings: &PlotSettings,
+    name: &str,
+    profile: &RedactionProfile,
+) -> SheetResult<PlotPage> {
+    let full = plot_document(doc, settings, name)?;
+    let (width, height) = settings.paper_mm();
+    let framed = PlotSettings {
+        area: PlotArea::Window {
+            min: full.origin,
+            max: (
+                full.origin.0 + width / full.scale,
+                full.origin.1 + height / full.scale,
+            ),
+        },
+        scale: PlotScale::Ratio(full.scale),
+        margin: 0.0,
+        ..settings.clone()
+    };
+
+    let redaction = profile.redact(doc);
+    let mut page = plot_document(&redaction.shared, &framed, name)?;
+    if profile.action == RedactionAction::Rasterize && !redaction.withheld.entities.is_empty() {
+        let withheld = plot_document(&redaction.withheld, &framed, name)?;
+        page.images.extend(withheld.rasterize(profile.raster_dpi));
+    }
+    Ok(page)
+}
+
+/// Color and lineweight a path is drawn with
+#[derive(Debug, Clone, Copy)]
+struct Pen {
+    color: Color,
+    weight: Option<f64>,
+}
+
+impl Pen {
+    /// Resolve an entity's overrides against its layer; `ByBlock` and
+    /// unresolvable values fall back to `inherited` (the insert's pen)
+    fn resolve(entity: &Entity, doc: &Document, inherited: Pen) -> Pen {
+        let layer = doc.get_layer(&entity.layer);
+        let color = entity
+            .color
+            .or_else(|| layer.map(|l| l.color))
+            .unwrap_or(inherited.color);
+        let weight = match entity.line_weight.unwrap_or(LineWeight::ByLayer) {
+            LineWeight::ByLayer => match layer.map(|l| l.line_weight) {
+                Some(LineWeight::Width(w)) => Some(w as f64 / 100.0),
+                Some(LineWeight::Hairline) => Some(0.0),
+                _ => inherited.weight,
+            },
+            LineWeight::Width(w) => Some(w as f64 / 100.0),
+            LineWeight::Hairline => Some(0.0),
+            LineWeight::Default | LineWeight::ByBlock => inherited.weight,
+        };
+        Pen { color, weight }
+    }
+}
+
+fn plottable(entity: &Entity, doc: &Document) -> bool {
+    entity.visible
+        && doc
+            .get_layer(&entity.layer)
+            .is_none_or(|l| l.visible && !l.frozen && l.plottable)
+}
+
+fn extents(page: &PlotPage) -> Option<((f64, f64), (f64, f64))> {
+    // Text counts with a rough box: average glyph width is about 0.6 of
+    // the height
+    let text_corners = page.texts.iter().flat_map(|t| {
+        let (sin, cos) = t.rotation.sin_cos();
+        let width = t.text.chars().count() as f64 * t.height * 0.6;
+        let (x, y) = t.position;
+        [
+            (x, y),
+            (x + width * cos, y + width * sin),
+            (x - t.height * sin, y + t.height * cos),
+            (
+                x + width * cos - t.height * sin,
+                y + width * sin + t.height * cos,
+            ),
+        ]
+    });
+    let points = page
+        .paths
+        .iter()
+        .flat_map(|p| p.points.iter().copied())
+        .chain(text_corners);
+    let mut bounds: Option<((f64, f64), (f64, f64))> = None;
+    for (x, y) in points {
+        let b = bounds.get_or_insert(((x, y), (x, y)));
+        b.0 = (b.0 .0.min(x), b.0 .1.min(y));
+        b.1 = (b.1 .0.max(x), b.1 .1.max(y));
+    }
+    bounds
+}
+
+fn flatten(
+    geometry: &GeometryType,
+    doc: &Document,
+    fields: &FieldContext,
+    t: &Transform,
+    depth: usize,
+    pen: Pen,
+    page: &mut PlotPage,
+) {
+    let mut stroke = |points: Vec<(f64, f64)>, closed: bool| {
+        if points.len() >= 2 {
+            page.paths.push(PlotPath {
+                points,
+                closed,
+                color: pen.color,
+                weight: pen.weight,
+            });
+        }
+    };
+
+    match geometry {
+        GeometryType::Line(l) => stroke(vec![t.apply(l.start), t.apply(l.end)], false),
+        GeometryType::Circle(c) => stroke(
+            arc_points(c.center, c.radius, c.radius, 0.0, 0.0, 2.0 * PI, t),
+            true,
+        ),
+        GeometryType::Arc(a) => {
+            let mut sweep = a.end_angle - a.start_angle;
+            while sweep <= 0.0 {
+                sweep += 2.0 * PI;
+            }
+            stroke(
+                arc_points(a.center, a.radius, a.radius, 0.0, a.start_angle, sweep, t),
+                false,
+            )
+        }
+        GeometryType::Ellipse(e) => stroke(
+            arc_points(
+                e.center,
+                e.major_axis,
+                e.minor_axis,
+                e.rotation,
+                0.0,
+                2.0 * PI,
+                t,
+            ),
+            true,
+        ),
+        GeometryType::Polyline(p) => stroke(polyline_points(p, t), p.closed),
+        GeometryType::Spline(s) => stroke(
+            spline_points(s).into_iter().map(|p| t.apply(p)).collect(),
+            s.closed,
+        ),
+        GeometryType::Hatch(h) => {
+            for boundary in &h.boundaries {
+                stroke(boundary.iter().map(|p| t.apply(*p)).collect(), true);
+            }
+            for line in h.lines() {
+                let (start, end) = (line.start, line.end);
+                stroke(
+                    vec![
+                        t.apply(Vec3::new(start.x, start.y, 0.0)),
+                        t.apply(Vec3::new(end.x, end.y, 0.0)),
+                    ],
+                    false,
+                );
+            }
+        }
+        GeometryType::Text(text) => page.texts.push(PlotText {
+            position: t.apply(text.position),
+            height: text.height * t.length_scale(),
+            rotation: text.rotation + t.rotation(),
+            text: evaluate(&text.text, doc, fields).into_owned(),
+        }),
+        GeometryType::MText(text) => {
+            let step = text.height * text.line_spacing.max(1.0) * 1.5;
+            let content = evaluate(&text.text, doc, fields);
+            for (i, line) in content.split("\\P").flat_map(|l| l.lines()).enumerate() {
+                let (s, c) = text.rotation.sin_cos();
+                let offset = step * i as f64;
+                let position = Vec3::new(
+                    text.position.x + offset * s,
+                    text.position.y - offset * c,
+                    0.0,
+                );
+                page.texts.push(PlotText {
+                    position: t.apply(position),
+                    height: text.height * t.length_scale(),
+                    rotation: text.rotation + t.rotation(),
+                    text: line.to_string(),
+                });
+            }
+        }
+        GeometryType::Insert(insert) => {
+            if depth >= MAX_BLOCK_DEPTH {
+                return;
+            }
+            let Some(block) = doc.get_block(&insert.block_name) else {
+                return;
+            };
+            let (sin, cos) = insert.rotation.sin_cos();
+            let local = Transform {
+                scale_x: insert.scale.x,
+                scale_y: insert.scale.y,
+                cos,
+                sin,
+                dx: insert.position.x,
+                dy: insert.position.y,
+            };
+            let base = Transform {
+                dx: -block.base_point.x,
+                dy: -block.base_point.y,
+                ..Transform::identity()
+            };
+            let nested = t.then(&local.then(&base));
+            for entity in block.entities.iter().filter(|e| e.visible) {
+                let entity_pen = Pen::resolve(entity, doc, pen);
+                flatten(&entity.geometry, doc, fields, &nested, depth + 1, entity_pen, page);
+            }
+        }
+        GeometryType::Point(_) | GeometryType::Dimension(_) | GeometryType::PointCloud(_) => {}
+    }
+}
+
+fn arc_points(
+    center: Vec3,
+    rx: f64,
+    ry: f64,
+    rotation: f64,
+    start: f64,
+    sweep: f64,
+    t: &Transform,
+) -> Vec<(f64, f64)> {
+    let turns = sweep.abs() / (2.0 * PI);
+    let segments = ((CIRCLE_SEGMENTS as f64 * turns - 1e-9).ceil() as usize).max(2);
+    let (rs, rc) = rotation.sin_cos();
+    // A closed sweep repeats the first point, so leave it out
+    let count = if (turns - 1.0).abs() < 1e-12 {
+        segments
+    } else {
+        segments + 1
+    };
+    (0..count)
+        .map(|i| {
+            let a = start + sweep * i as f64 / segments as f64;
+            let (x, y) = (rx * a.cos(), ry * a.sin());
+            t.apply(Vec3::new(
+                center.x + x * rc - y * rs,
+                center.y + x * rs + y * rc,
+                0.0,
+            ))
+        })
+        .collect()
+}
+
+/// Sampled spline, or its control polygon when the knots are unusable
+fn spline_points(s: &Spline) -> Vec<Vec3> {
+    let control: Vec<Point2D> = s
+        .control_points
+        .iter()
+        .map(|p| Point2D::new(p.x, p.y))
+        .collect();
+    let samples = (control.len() * 16).max(32);
+    let curve = match &s.weights {
+        Some(weights) => {
+            NurbsCurve::new(control.clone(), weights.clone(), s.knots.clone(), s.degree)
+                .map(|c| c.to_polyline(samples))
+        }
+        None if s.knots.is_empty() => {
+            BSpline::clamped(control.clone(), s.degree).map(|c| c.to_polyline(samples))
+        }
+        None => {
+            BSpline::new(control.clone(), s.knots.clone(), s.degree).map(|c| c.to_polyline(samples))
+        }
+    };
+    curve
+        .unwrap_or(control)
+        .into_iter()
+        .map(|p| Vec3::new(p.x, p.y, 0.0))
+        .collect()
+}
+
+fn polyline_points(p: &Polyline, t: &Transform) -> Vec<(f64, f64)> {
+    let n = p.vertices.len();
+    let mut points = Vec::with_capacity(n);
+    for i in 0..n {
+        let a = p.vertices[i].position;
+        points.push(t.apply(a));
+        let last = i + 1 == n;
+        let bulge = p.vertices[i].bulge;
+        if bulge.abs() < 1e-10 || (last && !p.closed) {
+            continue;
+        }
+        let b = p.vertices[(i + 1) % n].position;
+        // Bulge arc: included angle 4·atan(bulge), center off the chord
+        let theta = 4.0 * bulge.atan();
+        let (mx, my) = ((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
+        let (cx, cy) = (b.x - a.x, b.y - a.y);
+        let chord = (cx * cx + cy * cy).sqrt();
+        if chord < 1e-12 {
+            continue;
+        }
+        let sagitta_offset = chord / (2.0 * (theta / 2.0).tan());
+        let center = Vec3::new(
+            mx - cy / chord * sagitta_offset,
+            my + cx / chord * sagitta_offset,
+            0.0,
+        );
+        let radius = ((a.x - center.x).powi(2) + (a.y - center.y).powi(2)).sqrt();
+        let start = (a.y - center.y).atan2(a.x - center.x);
+        let arc = arc_points(center, radius, radius, 0.0, start, theta, t);
+        // Interior points only; the endpoints are the vertices themselves
+        points.extend(&arc[1..arc.len() - 1]);
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{
+        Block, Circle, Color, Insert, Layer, Line, LineType, LineWeight, Vertex, View,
+    };
+    use std::collections::HashMap;
+
+    fn line(x1: f64, y1: f64, x2: f64, y2: f64) -> Entity {
+        Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::new(x1, y1, 0.0),
+                end: Vec3::new(x2, y2, 0.0),
+            }),
+            "0".to_string(),
+        )
+    }
+
+    fn close(a: (f64, f64), b: (f64, f64)) -> bool {
+        (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_fit_extents_centers_drawing() {
+        let mut doc = Document::new();
+        doc.add_entity(line(0.0, 0.0, 1000.0, 500.0));
+        let settings = PlotSettings {
+            paper: PaperSize::A4,
+            orientation: Orientation::Landscape,
+            margin: 10.0,
+            ..PlotSettings::default()
+        };
+        let page = plot_document(&doc, &settings, "A-001").unwrap();
+        assert_eq!((page.width, page.height), (297.0, 210.0));
+
+        // Printable 277 x 190: width limits, scale 0.277
+        let points = &page.paths[0].points;
+        assert!(close(points[0], (10.0, 10.0 + (190.0 - 138.5) / 2.0)));
+        assert!(close(
+            points[1],
+            (287.0, 10.0 + (190.0 - 138.5) / 2.0 + 138.5)
+        ));
+    }
+
+    #[test]
+    fn test_view_and_ratio() {
+        let mut doc = Document::new();
+        doc.add_entity(line(0.0, 0.0, 100.0, 0.0));
+        doc.views.insert(
+            "DETAIL".to_string(),
+            View {
+                name: "DETAIL".to_string(),
+                center: Vec3::new(50.0, 0.0, 0.0),
+                height: 100.0,
+                width: 100.0,
+                target: Vec3::zero(),
+                direction: Vec3::unit_z(),
+                twist: 0.0,
+            },
+        );
+        let settings = PlotSettings {
+            paper: PaperSize::A4,
+            orientation: Orientation::Portrait,
+            area: PlotArea::View("DETAIL".to_string()),
+            scale: PlotScale::Ratio(1.0),
+            ..PlotSettings::default()
+        };
+        let page = plot_document(&doc, &settings, "D").unwrap();
+        // 100 mm window centered on 210 x 297 paper
+        assert!(close(page.paths[0].points[0], (55.0, 148.5)));
+        assert!(close(page.paths[0].points[1], (155.0, 148.5)));
+
+        let missing = PlotSettings {
+            area: PlotArea::View("NOPE".to_string()),
+            ..settings
+        };
+        assert!(matches!(
+            plot_document(&doc, &missing, "D"),
+            Err(SheetError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_insert_and_layers() {
+        let mut doc = Document::new();
+        doc.add_block(Block {
+            name: "MARK".to_string(),
+            base_point: Vec3::new(1.0, 0.0, 0.0),
+            entities: vec![line(1.0, 0.0, 2.0, 0.0)],
+            description: String::new(),
+        });
+        doc.add_entity(Entity::new(
+            GeometryType::Insert(Insert {
+                block_name: "MARK".to_string(),
+                position: Vec3::new(10.0, 10.0, 0.0),
+                scale: Vec3::new(2.0, 2.0, 1.0),
+                rotation: PI / 2.0,
+                attributes: HashMap::new(),
+            }),
+            "0".to_string(),
+        ));
+        doc.add_layer(Layer {
+            name: "NOPLOT".to_string(),
+            color: Color::red(),
+            line_type: LineType::Continuous,
+            line_weight: LineWeight::Default,
+            visible: true,
+            locked: false,
+            frozen: false,
+            plottable: false,
+            confidential: false,
+        });
+        let mut hidden = line(-100.0, -100.0, 100.0, 100.0);
+        hidden.layer = "NOPLOT".to_string();
+        doc.add_entity(hidden);
+
+        let settings = PlotSettings {
+            area: PlotArea::Window {
+                min: (0.0, 0.0),
+                max: (100.0, 100.0),
+            },
+            scale: PlotScale::Ratio(1.0),
+            margin: 0.0,
+            paper: PaperSize::Custom {
+                width: 100.0,
+                height: 100.0,
+            },
+            ..PlotSettings::default()
+        };
+        let page = plot_document(&doc, &settings, "M").unwrap();
+        assert_eq!(page.paths.len(), 1);
+        // Base point maps to the insert point, the line runs up 2 units
+        assert!(close(page.paths[0].points[0], (10.0, 10.0)));
+        assert!(close(page.paths[0].points[1], (10.0, 12.0)));
+    }
+
+    #[test]
+    fn test_redacted_plot_keeps_framing() {
+        let mut doc = Document::new();
+        doc.add_entity(line(0.0, 0.0, 100.0, 0.0));
+        doc.add_layer(Layer {
+            name: "COST".to_string(),
+            color: Color::black(),
+            line_type: LineType::Continuous,
+            line_weight: LineWeight::Default,
+            visible: true,
+            locked: false,
+            frozen: false,
+            plottable: true,
+            confidential: true,
+        });
+        let mut cost = line(0.0, 100.0, 100.0, 100.0);
+        cost.layer = "COST".to_string();
+        doc.add_entity(cost);
+
+        let settings = PlotSettings {
+            paper: PaperSize::Custom {
+                width: 100.0,
+                height: 100.0,
+            },
+            margin: 0.0,
+            ..PlotSettings::default()
+        };
+        let full = plot_document(&doc, &settings, "P").unwrap();
+        let profile = RedactionProfile::new("Tender");
+        let page = plot_redacted(&doc, &settings, "P", &profile).unwrap();
+        assert_eq!(page.paths.len(), 1);
+        assert!(page.images.is_empty());
+        assert!(close(page.paths[0].points[0], full.paths[0].points[0]));
+        assert!(close(page.paths[0].points[1], full.paths[0].points[1]));
+
+        let profile = profile.with_action(RedactionAction::Rasterize);
+        let page = plot_redacted(&doc, &settings, "P", &profile).unwrap();
+        assert_eq!(page.paths.len(), 1);
+        let image = &page.images[0];
+        // The withheld line runs along the top edge, clipped to the paper
+        assert!(image.position.1 > 99.0 && image.position.1 + image.size.1 >= 100.0);
+        assert_eq!(image.rgb.len(), (image.width * image.height * 3) as usize);
+        assert!(image.rgb.contains(&0) && image.rgb.contains(&255));
+    }
+
+    #[test]
+    fn test_pens_and_page_mapping() {
+        let mut doc = Document::new();
+        doc.add_layer(Layer {
+            name: "HEAVY".to_string(),
+            color: Color::blue(),
+            line_type: LineType::Continuous,
+            line_weight: LineWeight::Width(70),
+            visible: true,
+            locked: false,
+            frozen: false,
+            plottable: true,
+            confidential: false,
+        });
+        let mut heavy = line(0.0, 0.0, 100.0, 0.0);
+        heavy.layer = "HEAVY".to_string();
+        doc.add_entity(heavy);
+        let mut red = line(0.0, 50.0, 100.0, 50.0);
+        red.layer = "HEAVY".to_string();
+        red.color = Some(Color::red());
+        red.line_weight = Some(LineWeight::Hairline);
+        doc.add_entity(red);
+
+        let settings = PlotSettings {
+            area: PlotArea::Window {
+                min: (0.0, 0.0),
+                max: (100.0, 50.0),
+            },
+            scale: PlotScale::Ratio(2.0),
+            margin: 0.0,
+            paper: PaperSize::Custom {
+                width: 200.0,
+                height: 200.0,
+            },
+            ..PlotSettings::default()
+        };
+        let page = plot_document(&doc, &settings, "P").unwrap();
+        assert_eq!(page.paths[0].color, Color::blue());
+        assert_eq!(page.paths[0].weight, Some(0.7));
+        assert_eq!(page.paths[1].color, Color::red());
+        assert_eq!(page.paths[1].weight, Some(0.0));
+
+        // 100 x 50 at 2 mm per unit, centered vertically on 200 x 200
+        assert_eq!(page.scale, 2.0);
+        assert!(close(page.origin, (0.0, -25.0)));
+        let (x, y) = page.paths[1].points[0];
+        assert!(close(
+            (page.origin.0 + x / page.scale, page.origin.1 + y / page.scale),
+            (0.0, 50.0)
+        ));
+    }
+
+    #[test]
+    fn test_curves_tessellate() {
+        let mut doc = Document::new();
+        doc.add_entity(Entity::new(
+            GeometryType::Circle(Circle {
+                center: Vec3::zero(),
+                radius: 5.0,
+                normal: Vec3::unit_z(),
+            }),
+            "0".to_string(),
+        ));
+        let v = |x, y, bulge| Vertex {
+            position: Vec3::new(x, y, 0.0),
+            bulge,
+        };
+        doc.add_entity(Entity::new(
+            GeometryType::Polyline(Polyline {
+                vertices: vec![v(0.0, 0.0, 1.0), v(10.0, 0.0, 0.0)],
+                closed: false,
+            }),
+            "0".to_string(),
+        ));
+        let settings = PlotSettings {
+            area: PlotArea::Window {
+                min: (-50.0, -50.0),
+                max: (50.0, 50.0),
+            },
+            scale: PlotScale::Ratio(1.0),
+            margin: 0.0,
+            paper: PaperSize::Custom {
+                width: 100.0,
+                height: 100.0,
+            },
+            ..PlotSettings::default()
+        };
+        let page = plot_document(&doc, &settings, "C").unwrap();
+
+        let circle = &page.paths[0];
+        assert!(circle.closed);
+        assert_eq!(circle.points.len(), CIRCLE_SEGMENTS);
+        for &(x, y) in &circle.points {
+            assert!(((x - 50.0).hypot(y - 50.0) - 5.0).abs() < 1e-9);
+        }
+
+        // Semicircle (bulge 1) from (0,0) to (10,0), counter-clockwise: below the chord
+        let arc = &page.paths[1];
+        assert!(arc.points.len() > 10);
+        assert!(close(arc.points[0], (50.0, 50.0)));
+        assert!(close(*arc.points.last().unwrap(), (60.0, 50.0)));
+        for &(x, y) in &arc.points {
+            assert!(((x - 55.0).hypot(y - 50.0) - 5.0).abs() < 1e-9);
+            assert!(y <= 50.0 + 1e-9);
+        }
+    }
+}
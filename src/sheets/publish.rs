@@ -0,0 +1,418 @@
+//! Batch publishing
+//!
+//! Plots every published sheet of a set (plus the index sheet, if the set
+//! has one) and writes PDF or DWF files. A sheet that fails to load or plot
+//! is reported and skipped; the rest of the set still publishes. With a
+//! redaction profile, confidential layers are left out of every sheet (the
+//! generated index sheet has none).
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::dwf::write_dwf;
+use super::pdf::write_pdf;
+use super::plot::{plot_document, plot_redacted, PlotPage};
+use super::set::{fill_title_block, Sheet, SheetSet};
+use super::{SheetError, SheetResult};
+use crate::io::document::Document;
+use crate::io::fields::{evaluate_document, FieldContext};
+use crate::io::native::FormatDetector;
+use crate::io::redaction::{RedactionAction, RedactionProfile};
+
+/// Output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PublishFormat {
+    /// Vector PDF
+    Pdf,
+    /// ASCII DWF, one file per sheet
+    Dwf,
+}
+
+impl PublishFormat {
+    /// File extension
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PublishFormat::Pdf => "pdf",
+            PublishFormat::Dwf => "dwf",
+        }
+    }
+}
+
+/// Publish options
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublishOptions {
+    /// Output format
+    pub format: PublishFormat,
+    /// Directory for the output files (created if missing)
+    pub output_dir: PathBuf,
+    /// Write one multi-page PDF instead of a file per sheet (PDF only)
+    pub combine: bool,
+    /// Content withheld from the published sheets
+    #[serde(default)]
+    pub redaction: Option<RedactionProfile>,
+}
+
+impl PublishOptions {
+    /// One combined PDF
+    pub fn pdf(output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            format: PublishFormat::Pdf,
+            output_dir: output_dir.into(),
+            combine: true,
+            redaction: None,
+        }
+    }
+
+    /// One DWF per sheet
+    pub fn dwf(output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            format: PublishFormat::Dwf,
+            output_dir: output_dir.into(),
+            combine: false,
+            redaction: None,
+        }
+    }
+
+    /// Redact every sheet with `profile`
+    pub fn with_redaction(mut self, profile: RedactionProfile) -> Self {
+        self.redaction = Some(profile);
+        self
+    }
+}
+
+/// Result for one sheet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SheetOutcome {
+    /// Sheet number
+    pub number: String,
+    /// Sheet title
+    pub title: String,
+    /// Why the sheet was skipped
+    pub error: Option<String>,
+}
+
+/// Result of a publish run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PublishReport {
+    /// Files written
+    pub files: Vec<PathBuf>,
+    /// Every sheet in publish order
+    pub sheets: Vec<SheetOutcome>,
+}
+
+impl PublishReport {
+    /// Sheets that were skipped
+    pub fn failures(&self) -> impl Iterator<Item = &SheetOutcome> {
+        self.sheets.iter().filter(|s| s.error.is_some())
+    }
+
+    /// Whether every sheet was published
+    pub fn is_complete(&self) -> bool {
+        self.failures().next().is_none()
+    }
+}
+
+/// Publishes sheet sets
+#[derive(Debug, Clone)]
+pub struct Publisher {
+    options: PublishOptions,
+}
+
+impl Publisher {
+    /// Create a publisher
+    pub fn new(options: PublishOptions) -> Self {
+        Self { options }
+    }
+
+    /// Publish, loading drawings from disk
+    ///
+    /// Relative sheet paths resolve against `base_dir`, normally the folder
+    /// of the sheet set file.
+    pub fn publish(&self, set: &SheetSet, base_dir: &Path) -> SheetResult<PublishReport> {
+        self.publish_with(set, |path| {
+            let path = base_dir.join(path);
+            FormatDetector::load(&path).map_err(|e| SheetError::Load {
+                path: path.clone(),
+                message: e.to_string(),
+            })
+        })
+    }
+
+    /// Publish with a custom drawing loader
+    pub fn publish_with<F>(&self, set: &SheetSet, load: F) -> SheetResult<PublishReport>
+    where
+        F: FnMut(&Path) -> SheetResult<Document>,
+    {
+        set.validate()?;
+        // DWF pages carry no images, so rasterizing would be wasted
+        let redaction = self.options.redaction.clone().map(|profile| match self.options.format {
+            PublishFormat::Pdf => profile,
+            PublishFormat::Dwf => profile.with_action(RedactionAction::Strip),
+        });
+        let (pages, sheets) = plot_set_redacted(set, load, redaction.as_ref());
+
+        fs::create_dir_all(&self.options.output_dir)?;
+        let mut files = Vec::new();
+        if self.options.format == PublishFormat::Pdf && self.options.combine {
+            if !pages.is_empty() {
+                let path = self.output_path(&set.name);
+                fs::write(&path, write_pdf(&pages, &set.name))?;
+                files.push(path);
+            }
+        } else {
+            for page in &pages {
+                let path = self.output_path(&page.name);
+                let bytes = match self.options.format {
+                    PublishFormat::Pdf => write_pdf(std::slice::from_ref(page), &page.name),
+                    PublishFormat::Dwf => write_dwf(page),
+                };
+                fs::write(&path, bytes)?;
+                files.push(path);
+            }
+        }
+
+        for failure in sheets.iter().filter(|s| s.error.is_some()) {
+            log::warn!(
+                "Sheet {} '{}' not published: {}",
+                failure.number,
+                failure.title,
+                failure.error.as_deref().unwrap_or_default()
+            );
+        }
+        Ok(PublishReport { files, sheets })
+    }
+
+    fn output_path(&self, name: &str) -> PathBuf {
+        let stem: String = name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ' ') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.options.output_dir.join(format!(
+            "{}.{}",
+            stem.trim(),
+            self.options.format.extension()
+        ))
+    }
+}
+
+/// Plot the index sheet and every published sheet
+///
+/// Each drawing is loaded once, however many sheets use it.
+pub fn plot_set<F>(set: &SheetSet, load: F) -> (Vec<PlotPage>, Vec<SheetOutcome>)
+where
+    F: FnMut(&Path) -> SheetResult<Document>,
+{
+    plot_set_redacted(set, load, None)
+}
+
+/// [`plot_set`], withholding content from every sheet by `redaction`
+pub fn plot_set_redacted<F>(
+    set: &SheetSet,
+    mut load: F,
+    redaction: Option<&RedactionProfile>,
+) -> (Vec<PlotPage>, Vec<SheetOutcome>)
+where
+    F: FnMut(&Path) -> SheetResult<Document>,
+{
+    let mut pages = Vec::new();
+    let mut outcomes = Vec::new();
+
+    if let (Some(index), Some((doc, plot))) = (&set.index, set.index_document()) {
+        let name = format!("{} {}", index.number, index.title);
+        let result = plot_document(&doc, &plot, &name).map_err(|e| e.to_string());
+        outcomes.push(outcome(&index.number, &index.title, &result));
+        pages.extend(result.ok());
+    }
+
+    // Load failures are kept as messages so every sheet of a missing
+    // drawing reports the same error
+    let mut cache: HashMap<PathBuf, Result<Document, String>> = HashMap::new();
+    for entry in set.sheets().into_iter().filter(|e| e.sheet.publish) {
+        let sheet = entry.sheet;
+        let source = cache
+            .entry(sheet.document.clone())
+            .or_insert_with(|| load(&sheet.document).map_err(|e| e.to_string()));
+        let result = source.clone().and_then(|mut doc| {
+            let name = format!("{} {}", entry.number, sheet.title);
+            plot_sheet(set, sheet, &mut doc, &name, redaction).map_err(|e| e.to_string())
+        });
+        outcomes.push(outcome(&entry.number, &sheet.title, &result));
+        pages.extend(result.ok());
+    }
+    (pages, outcomes)
+}
+
+fn plot_sheet(
+    set: &SheetSet,
+    sheet: &Sheet,
+    doc: &mut Document,
+    name: &str,
+    redaction: Option<&RedactionProfile>,
+) -> SheetResult<PlotPage> {
+    let fields = set.fields_for(sheet.id)?;
+    fill_title_block(doc, set.title_block.as_deref(), &fields);
+    // Fix the remaining text fields as of this plot, with the sheet's own
+    // values and file name
+    let context = FieldContext::plot()
+        .with_file(&sheet.document)
+        .with_sheet(fields);
+    evaluate_document(doc, &context);
+    let settings = set.plot_settings(sheet);
+    match redaction {
+        Some(profile) => plot_redacted(doc, &settings, name, profile),
+        None => plot_document(doc, &settings, name),
+    }
+}
+
+fn outcome(number: &str, title: &str, result: &Result<PlotPage, String>) -> SheetOutcome {
+    SheetOutcome {
+        number: number.to_string(),
+        title: title.to_string(),
+        error: result.as_ref().err().cloned(),
+    }
+}
+
+/// Serialized publish request, the payload of a scheduled publish job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublishRequest {
+    /// Sheet set file
+    pub set_path: PathBuf,
+    /// Publish options
+    pub options: PublishOptions,
+}
+
+impl PublishRequest {
+    /// Load the set and publish it
+    pub fn run(&self) -> SheetResult<PublishReport> {
+        let set = SheetSet::load(&self.set_path)?;
+        let base_dir = self.set_path.parent().unwrap_or_else(|| Path::new("."));
+        Publisher::new(self.options.clone()).publish(&set, base_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{Entity, GeometryType, Text, TextAlignment, Vec3};
+    use crate::sheets::set::IndexSheet;
+    use uuid::Uuid;
+
+    fn drawing(label: &str) -> Document {
+        let mut doc = Document::new();
+        doc.add_entity(Entity::new(
+            GeometryType::Text(Text {
+                position: Vec3::new(0.0, 0.0, 0.0),
+                text: format!("{} {{{{PROJECT}}}} {{{{SHEET_NUMBER}}}} {{{{FILENAME}}}}", label),
+                height: 10.0,
+                rotation: 0.0,
+                style: "Standard".to_string(),
+                horizontal_alignment: TextAlignment::Left,
+                vertical_alignment: TextAlignment::Bottom,
+            }),
+            "0".to_string(),
+        ));
+        doc
+    }
+
+    fn set() -> SheetSet {
+        let mut set = SheetSet::new("Depot");
+        set.set_field("PROJECT", "Bus Depot");
+        set.index = Some(IndexSheet::default());
+        let arch = set.add_subset("Architectural", "A-");
+        arch.add_sheet(Sheet::new("Plan", "plan.cdy"));
+        arch.add_sheet(Sheet::new("Elevations", "plan.cdy"));
+        arch.add_sheet(Sheet::new("Missing", "missing.cdy"));
+        let mut draft = Sheet::new("Draft", "plan.cdy");
+        draft.publish = false;
+        arch.add_sheet(draft);
+        set
+    }
+
+    fn loader(loads: &mut usize) -> impl FnMut(&Path) -> SheetResult<Document> + '_ {
+        move |path| {
+            *loads += 1;
+            if path == Path::new("missing.cdy") {
+                Err(SheetError::Load {
+                    path: path.to_path_buf(),
+                    message: "not found".to_string(),
+                })
+            } else {
+                Ok(drawing("PLAN"))
+            }
+        }
+    }
+
+    #[test]
+    fn test_plot_set_fills_fields_and_caches() {
+        let mut loads = 0;
+        let (pages, outcomes) = plot_set(&set(), loader(&mut loads));
+        assert_eq!(loads, 2);
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[0].name, "G-000 Sheet Index");
+        assert_eq!(pages[2].name, "A-002 Elevations");
+        assert_eq!(pages[2].texts[0].text, "PLAN Bus Depot A-002 plan.cdy");
+
+        assert_eq!(outcomes.len(), 4);
+        assert_eq!(outcomes[3].number, "A-003");
+        assert!(outcomes[3].error.as_ref().unwrap().contains("not found"));
+    }
+
+    #[test]
+    fn test_publish_files() {
+        let dir = std::env::temp_dir().join(format!("publish-{}", Uuid::new_v4()));
+        let mut loads = 0;
+
+        let report = Publisher::new(PublishOptions::pdf(&dir))
+            .publish_with(&set(), loader(&mut loads))
+            .unwrap();
+        assert_eq!(report.files, vec![dir.join("Depot.pdf")]);
+        assert!(!report.is_complete());
+        assert_eq!(report.failures().count(), 1);
+        assert!(fs::read(&report.files[0]).unwrap().starts_with(b"%PDF"));
+
+        let report = Publisher::new(PublishOptions::dwf(&dir))
+            .publish_with(&set(), loader(&mut loads))
+            .unwrap();
+        assert_eq!(report.files.len(), 3);
+        assert!(report.files.contains(&dir.join("A-001 Plan.dwf")));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_redacted_publish() {
+        let profile = RedactionProfile::new("Tender").with_layer("0");
+        let mut loads = 0;
+        let (pages, outcomes) = plot_set_redacted(&set(), loader(&mut loads), Some(&profile));
+        assert_eq!(pages.len(), 3);
+        assert!(outcomes[1].error.is_none());
+        // The index sheet is generated, so only the drawings lose layer 0
+        assert!(!pages[0].texts.is_empty());
+        assert!(pages[1].texts.is_empty());
+        assert!(pages[2].texts.is_empty());
+
+        let options = PublishOptions::pdf("out").with_redaction(profile.clone());
+        let json = serde_json::to_string(&options).unwrap();
+        let back: PublishOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.redaction, Some(profile));
+    }
+
+    #[test]
+    fn test_duplicate_numbers_block_publish() {
+        let mut set = set();
+        set.subsets[0].sheets[1].number = Some("A-001".to_string());
+        let mut loads = 0;
+        let result = Publisher::new(PublishOptions::pdf(std::env::temp_dir()))
+            .publish_with(&set, loader(&mut loads));
+        assert!(matches!(result, Err(SheetError::DuplicateNumber(_))));
+        assert_eq!(loads, 0);
+    }
+}
@@ -0,0 +1,1401 @@
+//! Project documentation reports
+//!
+//! A [`ReportTemplate`] lists the blocks of a document: headings,
+//! paragraphs, tables, images, page breaks, and sections repeated for each
+//! row of a table. Templates are plain JSON, so each project can script its
+//! own documents, and their text takes the same `{{FIELD}}` placeholders as
+//! title blocks.
+//!
+//! [`ReportData::collect`] gathers what a template draws on from a sheet
+//! set: the drawing list, the current revision of each drawing, standards
+//! QA results when a standard is given, and a rendering of each sheet from
+//! the raster exporter. The same template and data render to PDF, HTML, or
+//! Markdown; HTML and Markdown embed the sheet images as PNG data URIs.
+//!
+//! A [`ReportRequest`] is the serialized form of one report, the payload of
+//! a scheduled report job.
+
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::changes::{FIELD_REVISION_CHANGES, FIELD_REVISION_DESCRIPTION};
+use super::pdf::write_pdf;
+use super::plot::{PlotArea, PlotImage, PlotPage, PlotPath, PlotText};
+use super::set::{substitute, Sheet, SheetSet, FIELD_SET_NAME, FIELD_SHEET_COUNT};
+use super::{SheetError, SheetResult};
+use crate::io::document::{Color, Document};
+use crate::io::export::RasterExportSettings;
+use crate::io::native::FormatDetector;
+use crate::io::raster::{encode_png, render_rows};
+use crate::standards::{CadStandard, Severity, StandardsChecker};
+
+/// Table listing every sheet: `number`, `title`, `subset`, `document`,
+/// `status`, and the sheet's own fields
+pub const TABLE_SHEETS: &str = "sheets";
+/// Table with the current revision of each drawing, newest first:
+/// `number`, `title`, `revision`, `date`, `author`, `description`,
+/// `changes` (the change requests the revision resolved)
+pub const TABLE_REVISIONS: &str = "revisions";
+/// Table of standards findings: `number`, `rule`, `severity`, `message`,
+/// `layer`
+pub const TABLE_QA: &str = "qa";
+/// Table with one standards result per sheet: `number`, `title`, `result`,
+/// `errors`, `warnings`
+pub const TABLE_QA_SUMMARY: &str = "qa_summary";
+
+/// Field holding the date the report was generated
+pub const FIELD_DATE: &str = "DATE";
+/// Field holding the overall standards result, `PASS` or `FAIL`
+pub const FIELD_QA_RESULT: &str = "QA_RESULT";
+
+/// Sheet field or document property holding the drawing's revision
+const REVISION_PROPERTY: &str = "REVISION";
+
+/// A4 portrait paper in millimeters
+const PAGE_SIZE: (f64, f64) = (210.0, 297.0);
+/// Page margin in millimeters
+const PAGE_MARGIN: f64 = 20.0;
+/// Cap heights of level 1, 2, and 3 headings in millimeters
+const HEADING_HEIGHTS: [f64; 3] = [5.0, 4.0, 3.2];
+/// Cap height of body text in millimeters
+const BODY_HEIGHT: f64 = 2.5;
+/// Cap height of table and caption text in millimeters
+const SMALL_HEIGHT: f64 = 2.1;
+/// Baseline-to-baseline distance as a multiple of the cap height
+const LINE_SPACING: f64 = 1.8;
+/// Average Helvetica advance as a multiple of the cap height
+const CHAR_WIDTH: f64 = 0.72;
+
+/// Report output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    /// Paginated A4 PDF
+    Pdf,
+    /// Single self-contained HTML page
+    Html,
+    /// Markdown
+    Markdown,
+}
+
+impl ReportFormat {
+    /// File extension
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Pdf => "pdf",
+            ReportFormat::Html => "html",
+            ReportFormat::Markdown => "md",
+        }
+    }
+
+    /// MIME type
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ReportFormat::Pdf => "application/pdf",
+            ReportFormat::Html => "text/html; charset=utf-8",
+            ReportFormat::Markdown => "text/markdown; charset=utf-8",
+        }
+    }
+}
+
+/// Table column: a header and the cell text, with `{{FIELD}}` placeholders
+/// taken from the row before the report fields
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportColumn {
+    /// Column header
+    pub header: String,
+    /// Cell text, e.g. `{{number}}`
+    pub value: String,
+}
+
+impl ReportColumn {
+    /// Column showing one row field
+    pub fn field(header: &str, field: &str) -> Self {
+        Self {
+            header: header.to_string(),
+            value: format!("{{{{{}}}}}", field),
+        }
+    }
+}
+
+/// One block of a report template
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReportBlock {
+    /// Heading
+    Heading {
+        /// Text
+        text: String,
+        /// Level from 1 (largest) to 3
+        #[serde(default = "default_level")]
+        level: u8,
+    },
+
+    /// Paragraph; line breaks in the text are kept
+    Paragraph {
+        /// Text
+        text: String,
+    },
+
+    /// Table with one line per data row
+    Table {
+        /// Data table name
+        table: String,
+        /// Columns
+        columns: Vec<ReportColumn>,
+        /// Only rows whose fields have these values
+        #[serde(default)]
+        filter: BTreeMap<String, String>,
+        /// Text shown instead of a table without rows
+        #[serde(default)]
+        empty: String,
+    },
+
+    /// Image from the report data
+    Image {
+        /// Image name, e.g. `{{number}}` inside a section repeated over
+        /// sheets
+        image: String,
+        /// Caption
+        #[serde(default)]
+        caption: String,
+    },
+
+    /// Blocks repeated for every row of a table, with the row's fields
+    /// available to their placeholders
+    Repeat {
+        /// Data table name
+        table: String,
+        /// Only rows whose fields have these values
+        #[serde(default)]
+        filter: BTreeMap<String, String>,
+        /// Blocks for each row
+        blocks: Vec<ReportBlock>,
+    },
+
+    /// Start a new page (a rule in HTML and Markdown)
+    PageBreak,
+}
+
+fn default_level() -> u8 {
+    1
+}
+
+/// Report layout
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportTemplate {
+    /// Template name, also the output file name
+    pub name: String,
+    /// Document title
+    pub title: String,
+    /// Blocks in order
+    pub blocks: Vec<ReportBlock>,
+}
+
+impl ReportTemplate {
+    /// Create an empty template
+    pub fn new(name: &str, title: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            title: title.to_string(),
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Append a block
+    pub fn with_block(mut self, block: ReportBlock) -> Self {
+        self.blocks.push(block);
+        self
+    }
+
+    /// Drawing list, revision history, standards results, and a page per
+    /// sheet image
+    pub fn project_documentation() -> Self {
+        let heading = |text: &str, level: u8| ReportBlock::Heading {
+            text: text.to_string(),
+            level,
+        };
+        Self::new(
+            "project-documentation",
+            "{{SHEET_SET}} Project Documentation",
+        )
+        .with_block(heading("{{SHEET_SET}}", 1))
+        .with_block(ReportBlock::Paragraph {
+            text: "Issued {{DATE}}\n{{SHEET_COUNT}} sheets".to_string(),
+        })
+        .with_block(heading("Drawing List", 2))
+        .with_block(ReportBlock::Table {
+            table: TABLE_SHEETS.to_string(),
+            columns: vec![
+                ReportColumn::field("Number", "number"),
+                ReportColumn::field("Title", "title"),
+                ReportColumn::field("Discipline", "subset"),
+                ReportColumn::field("Drawing", "document"),
+            ],
+            filter: BTreeMap::new(),
+            empty: "The set has no sheets.".to_string(),
+        })
+        .with_block(heading("Revision History", 2))
+        .with_block(ReportBlock::Table {
+            table: TABLE_REVISIONS.to_string(),
+            columns: vec![
+                ReportColumn::field("Number", "number"),
+                ReportColumn::field("Rev", "revision"),
+                ReportColumn::field("Date", "date"),
+                ReportColumn::field("Author", "author"),
+                ReportColumn::field("Description", "description"),
+                ReportColumn::field("Changes", "changes"),
+            ],
+            filter: BTreeMap::new(),
+            empty: "No drawings could be read.".to_string(),
+        })
+        .with_block(heading("Quality Checks", 2))
+        .with_block(ReportBlock::Table {
+            table: TABLE_QA_SUMMARY.to_string(),
+            columns: vec![
+                ReportColumn::field("Number", "number"),
+                ReportColumn::field("Result", "result"),
+                ReportColumn::field("Errors", "errors"),
+                ReportColumn::field("Warnings", "warnings"),
+            ],
+            filter: BTreeMap::new(),
+            empty: "No standard was checked.".to_string(),
+        })
+        .with_block(ReportBlock::Table {
+            table: TABLE_QA.to_string(),
+            columns: vec![
+                ReportColumn::field("Number", "number"),
+                ReportColumn::field("Rule", "rule"),
+                ReportColumn::field("Severity", "severity"),
+                ReportColumn::field("Finding", "message"),
+            ],
+            filter: BTreeMap::new(),
+            empty: String::new(),
+        })
+        .with_block(ReportBlock::Repeat {
+            table: TABLE_SHEETS.to_string(),
+            filter: BTreeMap::new(),
+            blocks: vec![
+                ReportBlock::PageBreak,
+                heading("{{number}} {{title}}", 2),
+                ReportBlock::Image {
+                    image: "{{number}}".to_string(),
+                    caption: "{{document}}".to_string(),
+                },
+            ],
+        })
+    }
+
+    /// Load a template from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> SheetResult<Self> {
+        let template: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Save the template as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> SheetResult<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Check heading levels and that every table has columns
+    pub fn validate(&self) -> SheetResult<()> {
+        fn check(blocks: &[ReportBlock]) -> SheetResult<()> {
+            for block in blocks {
+                match block {
+                    ReportBlock::Heading { level, text } if !(1..=3).contains(level) => {
+                        return Err(SheetError::Template(format!(
+                            "heading '{}' has level {}, expected 1 to 3",
+                            text, level
+                        )));
+                    }
+                    ReportBlock::Table { table, columns, .. } if columns.is_empty() => {
+                        return Err(SheetError::Template(format!(
+                            "table '{}' has no columns",
+                            table
+                        )));
+                    }
+                    ReportBlock::Repeat { blocks, .. } => check(blocks)?,
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+        check(&self.blocks)
+    }
+
+    /// Whether any block shows an image, so sheets need rendering
+    pub fn uses_images(&self) -> bool {
+        fn any(blocks: &[ReportBlock]) -> bool {
+            blocks.iter().any(|block| match block {
+                ReportBlock::Image { .. } => true,
+                ReportBlock::Repeat { blocks, .. } => any(blocks),
+                _ => false,
+            })
+        }
+        any(&self.blocks)
+    }
+
+    /// Render the report
+    pub fn render(&self, data: &ReportData, format: ReportFormat) -> SheetResult<Vec<u8>> {
+        let mut title = self.title.clone();
+        substitute(&mut title, &data.fields);
+        let mut elements = Vec::new();
+        expand(&self.blocks, data, &data.fields, &mut elements);
+        Ok(match format {
+            ReportFormat::Pdf => render_pdf(&title, &elements),
+            ReportFormat::Html => render_html(&title, &elements)?.into_bytes(),
+            ReportFormat::Markdown => render_markdown(&title, &elements)?.into_bytes(),
+        })
+    }
+}
+
+/// Packed RGB image, rows top to bottom
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportImage {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Packed RGB rows, top to bottom
+    pub rgb: Vec<u8>,
+}
+
+/// Row of a report table, by field name
+pub type ReportRow = BTreeMap<String, String>;
+
+/// What a report template draws on
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReportData {
+    /// Report-wide fields
+    pub fields: BTreeMap<String, String>,
+    /// Tables by name
+    pub tables: BTreeMap<String, Vec<ReportRow>>,
+    /// Images by name
+    pub images: BTreeMap<String, ReportImage>,
+}
+
+/// What [`ReportData::collect`] gathers besides the drawing list
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectOptions {
+    /// Audit every drawing against this standard
+    pub standard: Option<CadStandard>,
+    /// Render an image of every sheet
+    pub images: bool,
+    /// Image width in pixels
+    pub image_width: u32,
+}
+
+impl Default for CollectOptions {
+    fn default() -> Self {
+        Self {
+            standard: None,
+            images: true,
+            image_width: 1200,
+        }
+    }
+}
+
+impl ReportData {
+    /// Empty data
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a report field
+    pub fn set_field(&mut self, name: &str, value: &str) {
+        self.fields.insert(name.to_string(), value.to_string());
+    }
+
+    /// Append a row to a table, creating the table if needed
+    pub fn push_row(&mut self, table: &str, row: ReportRow) {
+        self.tables.entry(table.to_string()).or_default().push(row);
+    }
+
+    /// Gather the set's fields, drawing list, revisions, standards results,
+    /// and sheet images
+    ///
+    /// Every sheet is listed; a drawing that fails to load gets its error in
+    /// the `status` column and is left out of the other tables. Each drawing
+    /// is loaded once, however many sheets use it.
+    pub fn collect<F>(set: &SheetSet, options: &CollectOptions, mut load: F) -> SheetResult<Self>
+    where
+        F: FnMut(&Path) -> SheetResult<Document>,
+    {
+        let checker = options
+            .standard
+            .clone()
+            .map(StandardsChecker::new)
+            .transpose()
+            .map_err(|e| SheetError::Template(e.to_string()))?;
+
+        let sheets = set.sheets();
+        let mut data = Self::new();
+        data.fields = set.fields.clone();
+        data.set_field(FIELD_SET_NAME, &set.name);
+        data.set_field(FIELD_SHEET_COUNT, &sheets.len().to_string());
+        data.set_field(
+            FIELD_DATE,
+            &chrono::Utc::now().format("%Y-%m-%d").to_string(),
+        );
+        for table in [TABLE_SHEETS, TABLE_REVISIONS, TABLE_QA, TABLE_QA_SUMMARY] {
+            data.tables.insert(table.to_string(), Vec::new());
+        }
+
+        let mut documents: HashMap<PathBuf, Result<Document, String>> = HashMap::new();
+        let mut revisions = Vec::new();
+        let mut passed = true;
+        for entry in &sheets {
+            let sheet = entry.sheet;
+            let doc = documents
+                .entry(sheet.document.clone())
+                .or_insert_with(|| load(&sheet.document).map_err(|e| e.to_string()));
+
+            let mut row = sheet.fields.clone();
+            row.insert("number".to_string(), entry.number.clone());
+            row.insert("title".to_string(), sheet.title.clone());
+            row.insert("subset".to_string(), entry.subset.name.clone());
+            row.insert("document".to_string(), sheet.document.display().to_string());
+            let doc = match doc {
+                Ok(doc) => {
+                    row.insert("status".to_string(), "ok".to_string());
+                    doc
+                }
+                Err(error) => {
+                    row.insert("status".to_string(), error.clone());
+                    data.push_row(TABLE_SHEETS, row);
+                    continue;
+                }
+            };
+            data.push_row(TABLE_SHEETS, row);
+
+            let sheet_field = |name: &str| {
+                sheet
+                    .fields
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                    .map(|(_, v)| v.clone())
+            };
+            let revision = sheet_field(REVISION_PROPERTY)
+                .or_else(|| {
+                    doc.metadata
+                        .custom_properties
+                        .get(REVISION_PROPERTY)
+                        .cloned()
+                })
+                .unwrap_or_else(|| "-".to_string());
+            let description = sheet_field(FIELD_REVISION_DESCRIPTION)
+                .unwrap_or_else(|| doc.metadata.comments.clone());
+            revisions.push((
+                doc.metadata.modified,
+                ReportRow::from([
+                    ("number".to_string(), entry.number.clone()),
+                    ("title".to_string(), sheet.title.clone()),
+                    ("revision".to_string(), revision),
+                    (
+                        "date".to_string(),
+                        doc.metadata.modified.format("%Y-%m-%d").to_string(),
+                    ),
+                    ("author".to_string(), doc.metadata.author.clone()),
+                    ("description".to_string(), description),
+                    (
+                        "changes".to_string(),
+                        sheet_field(FIELD_REVISION_CHANGES).unwrap_or_default(),
+                    ),
+                ]),
+            ));
+
+            if let Some(checker) = &checker {
+                let report = checker.check(doc);
+                passed &= report.passed();
+                data.push_row(
+                    TABLE_QA_SUMMARY,
+                    ReportRow::from([
+                        ("number".to_string(), entry.number.clone()),
+                        ("title".to_string(), sheet.title.clone()),
+                        (
+                            "result".to_string(),
+                            if report.passed() { "PASS" } else { "FAIL" }.to_string(),
+                        ),
+                        ("errors".to_string(), report.errors().count().to_string()),
+                        (
+                            "warnings".to_string(),
+                            report.warnings().count().to_string(),
+                        ),
+                    ]),
+                );
+                for finding in &report.findings {
+                    let severity = match finding.severity {
+                        Severity::Error => "error",
+                        Severity::Warning => "warning",
+                    };
+                    data.push_row(
+                        TABLE_QA,
+                        ReportRow::from([
+                            ("number".to_string(), entry.number.clone()),
+                            ("rule".to_string(), finding.rule.id().to_string()),
+                            ("severity".to_string(), severity.to_string()),
+                            ("message".to_string(), finding.message.clone()),
+                            (
+                                "layer".to_string(),
+                                finding.layer.clone().unwrap_or_default(),
+                            ),
+                        ]),
+                    );
+                }
+            }
+
+            if options.images {
+                match sheet_image(doc, set, entry.sheet, options.image_width) {
+                    Ok(image) => {
+                        data.images.insert(entry.number.clone(), image);
+                    }
+                    Err(error) => log::warn!("No image of sheet {}: {}", entry.number, error),
+                }
+            }
+        }
+
+        // Stable sort keeps set order among drawings saved together
+        revisions.sort_by_key(|r| std::cmp::Reverse(r.0));
+        data.tables.insert(
+            TABLE_REVISIONS.to_string(),
+            revisions.into_iter().map(|(_, row)| row).collect(),
+        );
+        if checker.is_some() {
+            data.set_field(FIELD_QA_RESULT, if passed { "PASS" } else { "FAIL" });
+        }
+        Ok(data)
+    }
+}
+
+/// Render a sheet's plot area with the raster exporter
+///
+/// The image has the shape of the plotted window, or of the sheet's paper
+/// when it plots the extents.
+fn sheet_image(
+    doc: &Document,
+    set: &SheetSet,
+    sheet: &Sheet,
+    width: u32,
+) -> SheetResult<ReportImage> {
+    let plot = set.plot_settings(sheet);
+    let window = match &plot.area {
+        PlotArea::Extents => None,
+        PlotArea::View(name) => {
+            let view = doc
+                .views
+                .get(name)
+                .ok_or_else(|| SheetError::NotFound(format!("view '{}'", name)))?;
+            Some((
+                (
+                    view.center.x - view.width / 2.0,
+                    view.center.y - view.height / 2.0,
+                ),
+                (
+                    view.center.x + view.width / 2.0,
+                    view.center.y + view.height / 2.0,
+                ),
+            ))
+        }
+        PlotArea::Window { min, max } => Some((*min, *max)),
+    };
+    let aspect = match window {
+        Some((min, max)) if max.0 > min.0 && max.1 > min.1 => (max.1 - min.1) / (max.0 - min.0),
+        _ => {
+            let (w, h) = plot.paper_mm();
+            h / w
+        }
+    };
+    let width = width.max(1);
+    let settings = RasterExportSettings {
+        width,
+        height: ((width as f64 * aspect).round() as u32).max(1),
+        window,
+        line_width: plot.line_width,
+        world_file: false,
+        ..RasterExportSettings::default()
+    };
+
+    let mut rgb = Vec::with_capacity(settings.width as usize * settings.height as usize * 3);
+    render_rows(doc, &settings, |rows| {
+        rgb.extend_from_slice(rows);
+        Ok(())
+    })
+    .map_err(|e| SheetError::Plot(e.to_string()))?;
+    Ok(ReportImage {
+        width: settings.width,
+        height: settings.height,
+        rgb,
+    })
+}
+
+/// Serialized report request, the payload of a scheduled report job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportRequest {
+    /// Sheet set file
+    pub set_path: PathBuf,
+    /// Template
+    pub template: ReportTemplate,
+    /// Output format
+    pub format: ReportFormat,
+    /// Folder the report is written to
+    pub output_dir: PathBuf,
+    /// Standard file to audit the drawings against
+    #[serde(default)]
+    pub standard: Option<PathBuf>,
+    /// Sheet image width in pixels
+    #[serde(default = "default_image_width")]
+    pub image_width: u32,
+}
+
+fn default_image_width() -> u32 {
+    CollectOptions::default().image_width
+}
+
+impl ReportRequest {
+    /// Request a report written to `output_dir`
+    pub fn new(
+        set_path: impl Into<PathBuf>,
+        template: ReportTemplate,
+        format: ReportFormat,
+        output_dir: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            set_path: set_path.into(),
+            template,
+            format,
+            output_dir: output_dir.into(),
+            standard: None,
+            image_width: default_image_width(),
+        }
+    }
+
+    /// Load the set and its drawings and render the report
+    ///
+    /// Relative drawing paths resolve against the folder of the set file.
+    pub fn generate(&self) -> SheetResult<Vec<u8>> {
+        self.template.validate()?;
+        let set = SheetSet::load(&self.set_path)?;
+        let base_dir = self.set_path.parent().unwrap_or_else(|| Path::new("."));
+        let standard = match &self.standard {
+            Some(path) => Some(CadStandard::load(path).map_err(|e| SheetError::Load {
+                path: path.clone(),
+                message: e.to_string(),
+            })?),
+            None => None,
+        };
+        let options = CollectOptions {
+            standard,
+            images: self.template.uses_images(),
+            image_width: self.image_width,
+        };
+        let data = ReportData::collect(&set, &options, |path| {
+            let path = base_dir.join(path);
+            FormatDetector::load(&path).map_err(|e| SheetError::Load {
+                path: path.clone(),
+                message: e.to_string(),
+            })
+        })?;
+        self.template.render(&data, self.format)
+    }
+
+    /// Generate the report and write it into the output folder
+    pub fn run(&self) -> SheetResult<PathBuf> {
+        let bytes = self.generate()?;
+        fs::create_dir_all(&self.output_dir)?;
+        let path = self.output_path();
+        fs::write(&path, bytes)?;
+        Ok(path)
+    }
+
+    /// File the report is written to
+    pub fn output_path(&self) -> PathBuf {
+        let stem: String = self
+            .template
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ' ') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.output_dir
+            .join(format!("{}.{}", stem.trim(), self.format.extension()))
+    }
+}
+
+/// A template block with its placeholders and rows resolved
+#[derive(Debug, Clone, PartialEq)]
+enum Element<'a> {
+    Heading(u8, String),
+    Paragraph(String),
+    Table {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    Image(&'a ReportImage, String),
+    PageBreak,
+}
+
+fn matches(row: &ReportRow, filter: &BTreeMap<String, String>) -> bool {
+    filter.iter().all(|(field, value)| {
+        row.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(field))
+            .is_some_and(|(_, v)| v == value)
+    })
+}
+
+fn fill(text: &str, fields: &BTreeMap<String, String>) -> String {
+    let mut text = text.to_string();
+    substitute(&mut text, fields);
+    text
+}
+
+fn expand<'a>(
+    blocks: &[ReportBlock],
+    data: &'a ReportData,
+    fields: &BTreeMap<String, String>,
+    out: &mut Vec<Element<'a>>,
+) {
+    let rows = |table: &str, filter: &BTreeMap<String, String>| -> Vec<BTreeMap<String, String>> {
+        data.tables
+            .get(table)
+            .into_iter()
+            .flatten()
+            .filter(|row| matches(row, filter))
+            .map(|row| {
+                let mut scope = fields.clone();
+                scope.extend(row.iter().map(|(k, v)| (k.clone(), v.clone())));
+                scope
+            })
+            .collect()
+    };
+
+    for block in blocks {
+        match block {
+            ReportBlock::Heading { text, level } => {
+                out.push(Element::Heading(*level, fill(text, fields)))
+            }
+            ReportBlock::Paragraph { text } => out.push(Element::Paragraph(fill(text, fields))),
+            ReportBlock::Table {
+                table,
+                columns,
+                filter,
+                empty,
+            } => {
+                let rows = rows(table, filter);
+                if rows.is_empty() {
+                    if !empty.is_empty() {
+                        out.push(Element::Paragraph(fill(empty, fields)));
+                    }
+                    continue;
+                }
+                out.push(Element::Table {
+                    headers: columns.iter().map(|c| fill(&c.header, fields)).collect(),
+                    rows: rows
+                        .iter()
+                        .map(|scope| columns.iter().map(|c| fill(&c.value, scope)).collect())
+                        .collect(),
+                });
+            }
+            ReportBlock::Image { image, caption } => {
+                let name = fill(image, fields);
+                let caption = fill(caption, fields);
+                match data.images.get(&name) {
+                    Some(image) => out.push(Element::Image(image, caption)),
+                    None => out.push(Element::Paragraph(format!("[No image for {}]", name))),
+                }
+            }
+            ReportBlock::Repeat {
+                table,
+                filter,
+                blocks,
+            } => {
+                for scope in rows(table, filter) {
+                    expand(blocks, data, &scope, out);
+                }
+            }
+            ReportBlock::PageBreak => out.push(Element::PageBreak),
+        }
+    }
+}
+
+fn png_data_uri(image: &ReportImage) -> SheetResult<String> {
+    let png = encode_png(image.width, image.height, 96, &image.rgb)?;
+    Ok(format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(png)
+    ))
+}
+
+fn render_markdown(title: &str, elements: &[Element]) -> SheetResult<String> {
+    let cell = |text: &str| text.replace('|', "\\|").replace('\n', " ");
+    let mut s = String::new();
+    let _ = writeln!(s, "<!-- {} -->", title);
+    for element in elements {
+        s.push('\n');
+        match element {
+            Element::Heading(level, text) => {
+                let _ = writeln!(s, "{} {}", "#".repeat(*level as usize), text);
+            }
+            // Two trailing spaces make a Markdown line break
+            Element::Paragraph(text) => {
+                let _ = writeln!(s, "{}", text.lines().collect::<Vec<_>>().join("  \n"));
+            }
+            Element::Table { headers, rows } => {
+                let line = |cells: &[String]| {
+                    format!(
+                        "| {} |",
+                        cells
+                            .iter()
+                            .map(|c| cell(c))
+                            .collect::<Vec<_>>()
+                            .join(" | ")
+                    )
+                };
+                let _ = writeln!(s, "{}", line(headers));
+                let _ = writeln!(s, "|{}", " --- |".repeat(headers.len()));
+                for row in rows {
+                    let _ = writeln!(s, "{}", line(row));
+                }
+            }
+            Element::Image(image, caption) => {
+                let _ = writeln!(s, "![{}]({})", cell(caption), png_data_uri(image)?);
+                if !caption.is_empty() {
+                    let _ = writeln!(s, "\n*{}*", caption);
+                }
+            }
+            Element::PageBreak => s.push_str("---\n"),
+        }
+    }
+    Ok(s)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut s = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => s.push_str("&amp;"),
+            '<' => s.push_str("&lt;"),
+            '>' => s.push_str("&gt;"),
+            '"' => s.push_str("&quot;"),
+            '\'' => s.push_str("&#39;"),
+            _ => s.push(c),
+        }
+    }
+    s
+}
+
+fn render_html(title: &str, elements: &[Element]) -> SheetResult<String> {
+    let mut s = String::new();
+    s.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(s, "<title>{}</title>", escape_html(title));
+    s.push_str(
+        "<style>\n\
+         body { font-family: Helvetica, Arial, sans-serif; max-width: 60em; margin: 2em auto; }\n\
+         table { border-collapse: collapse; margin: 1em 0; }\n\
+         th, td { border: 1px solid #999; padding: 0.25em 0.6em; text-align: left; }\n\
+         figure img { max-width: 100%; border: 1px solid #ccc; }\n\
+         hr.page { page-break-after: always; border: none; }\n\
+         </style>\n</head>\n<body>\n",
+    );
+    for element in elements {
+        match element {
+            Element::Heading(level, text) => {
+                let _ = writeln!(s, "<h{0}>{1}</h{0}>", level, escape_html(text));
+            }
+            Element::Paragraph(text) => {
+                let lines: Vec<String> = text.lines().map(escape_html).collect();
+                let _ = writeln!(s, "<p>{}</p>", lines.join("<br>"));
+            }
+            Element::Table { headers, rows } => {
+                s.push_str("<table>\n<thead><tr>");
+                for header in headers {
+                    let _ = write!(s, "<th>{}</th>", escape_html(header));
+                }
+                s.push_str("</tr></thead>\n<tbody>\n");
+                for row in rows {
+                    s.push_str("<tr>");
+                    for cell in row {
+                        let _ = write!(s, "<td>{}</td>", escape_html(cell));
+                    }
+                    s.push_str("</tr>\n");
+                }
+                s.push_str("</tbody>\n</table>\n");
+            }
+            Element::Image(image, caption) => {
+                let _ = write!(
+                    s,
+                    "<figure><img src=\"{}\" alt=\"{}\">",
+                    png_data_uri(image)?,
+                    escape_html(caption)
+                );
+                if !caption.is_empty() {
+                    let _ = write!(s, "<figcaption>{}</figcaption>", escape_html(caption));
+                }
+                s.push_str("</figure>\n");
+            }
+            Element::PageBreak => s.push_str("<hr class=\"page\">\n"),
+        }
+    }
+    s.push_str("</body>\n</html>\n");
+    Ok(s)
+}
+
+/// Flows report elements onto A4 pages
+struct PdfLayout {
+    pages: Vec<PlotPage>,
+    /// Top of the free space on the current page
+    y: f64,
+    /// Latest level 1 heading, naming the pages it covers
+    section: String,
+}
+
+impl PdfLayout {
+    fn width() -> f64 {
+        PAGE_SIZE.0 - 2.0 * PAGE_MARGIN
+    }
+
+    /// Lowest baseline content may use, above the footer
+    fn bottom() -> f64 {
+        PAGE_MARGIN + SMALL_HEIGHT * LINE_SPACING
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(PlotPage {
+            name: self.section.clone(),
+            width: PAGE_SIZE.0,
+            height: PAGE_SIZE.1,
+            line_width: 0.2,
+            scale: 1.0,
+            origin: (0.0, 0.0),
+            paths: Vec::new(),
+            texts: Vec::new(),
+            images: Vec::new(),
+        });
+        self.y = PAGE_SIZE.1 - PAGE_MARGIN;
+    }
+
+    /// Start a new page unless `height` still fits on this one
+    fn reserve(&mut self, height: f64) {
+        let fresh = self.y >= PAGE_SIZE.1 - PAGE_MARGIN;
+        if self.pages.is_empty() || (self.y - height < Self::bottom() && !fresh) {
+            self.new_page();
+        }
+    }
+
+    fn page(&mut self) -> &mut PlotPage {
+        self.pages.last_mut().expect("layout always has a page")
+    }
+
+    fn text(&mut self, x: f64, height: f64, text: String) {
+        let y = self.y;
+        self.page().texts.push(PlotText {
+            position: (x, y),
+            height,
+            rotation: 0.0,
+            text,
+        });
+    }
+
+    fn rule(&mut self, y: f64) {
+        self.page().paths.push(PlotPath {
+            points: vec![(PAGE_MARGIN, y), (PAGE_SIZE.0 - PAGE_MARGIN, y)],
+            closed: false,
+            color: Color::black(),
+            weight: None,
+        });
+    }
+
+    fn lines(&mut self, text: &str, height: f64) {
+        let columns = (Self::width() / (height * CHAR_WIDTH)).floor().max(1.0) as usize;
+        for line in text.lines().flat_map(|line| wrap(line, columns)) {
+            self.reserve(height * LINE_SPACING);
+            self.y -= height * LINE_SPACING;
+            self.text(PAGE_MARGIN, height, line);
+        }
+        self.y -= height;
+    }
+
+    fn table(&mut self, headers: &[String], rows: &[Vec<String>]) {
+        // Columns share the width in proportion to their longest text
+        let chars: Vec<f64> = (0..headers.len())
+            .map(|i| {
+                rows.iter()
+                    .filter_map(|row| row.get(i))
+                    .chain([&headers[i]])
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or(1)
+                    .clamp(4, 60) as f64
+            })
+            .collect();
+        let total: f64 = chars.iter().sum();
+        let mut x = PAGE_MARGIN;
+        let columns: Vec<(f64, usize)> = chars
+            .iter()
+            .map(|c| {
+                let width = Self::width() * c / total;
+                let at = x;
+                x += width;
+                (
+                    at,
+                    ((width - 2.0) / (SMALL_HEIGHT * CHAR_WIDTH))
+                        .floor()
+                        .max(1.0) as usize,
+                )
+            })
+            .collect();
+
+        let row_height = SMALL_HEIGHT * LINE_SPACING;
+        let header = |layout: &mut Self| {
+            layout.reserve(row_height * 2.0);
+            layout.y -= row_height;
+            for ((x, fit), text) in columns.iter().zip(headers) {
+                layout.text(*x, SMALL_HEIGHT, truncate(text, *fit));
+            }
+            let y = layout.y - SMALL_HEIGHT * 0.5;
+            layout.rule(y);
+        };
+        header(self);
+        for row in rows {
+            let before = self.pages.len();
+            self.reserve(row_height);
+            if self.pages.len() != before {
+                header(self);
+            }
+            self.y -= row_height;
+            for ((x, fit), text) in columns.iter().zip(row) {
+                self.text(*x, SMALL_HEIGHT, truncate(text, *fit));
+            }
+        }
+        let y = self.y - SMALL_HEIGHT * 0.5;
+        self.rule(y);
+        self.y -= SMALL_HEIGHT * 2.0;
+    }
+
+    fn image(&mut self, image: &ReportImage, caption: &str) {
+        let caption_height = if caption.is_empty() {
+            0.0
+        } else {
+            SMALL_HEIGHT * LINE_SPACING
+        };
+        let tallest = PAGE_SIZE.1 - PAGE_MARGIN - Self::bottom() - caption_height;
+        let aspect = image.height as f64 / image.width.max(1) as f64;
+        let mut size = (Self::width(), Self::width() * aspect);
+        if size.1 > tallest {
+            size = (tallest / aspect, tallest);
+        }
+        self.reserve(size.1 + caption_height);
+        self.y -= size.1;
+        let position = (PAGE_MARGIN + (Self::width() - size.0) / 2.0, self.y);
+        self.page().images.push(PlotImage {
+            position,
+            size,
+            width: image.width,
+            height: image.height,
+            rgb: image.rgb.clone(),
+        });
+        if !caption.is_empty() {
+            self.y -= caption_height;
+            self.text(position.0, SMALL_HEIGHT, caption.to_string());
+        }
+        self.y -= BODY_HEIGHT;
+    }
+}
+
+/// Split a line into lines of at most `columns` characters at spaces;
+/// longer words are broken
+fn wrap(line: &str, columns: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in line.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        while word.len() > columns {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            lines.push(word.drain(..columns).collect());
+        }
+        let length = current.chars().count();
+        if length > 0 && length + 1 + word.len() > columns {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.extend(word);
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+fn truncate(text: &str, columns: usize) -> String {
+    let text = text.replace('\n', " ");
+    if text.chars().count() <= columns {
+        return text;
+    }
+    let mut short: String = text.chars().take(columns.saturating_sub(3)).collect();
+    short.push_str("...");
+    short
+}
+
+fn render_pdf(title: &str, elements: &[Element]) -> Vec<u8> {
+    let mut layout = PdfLayout {
+        pages: Vec::new(),
+        y: 0.0,
+        section: title.to_string(),
+    };
+    layout.new_page();
+    for element in elements {
+        match element {
+            Element::Heading(level, text) => {
+                let height = HEADING_HEIGHTS[(*level as usize).clamp(1, 3) - 1];
+                // Keep a heading with at least one line of what follows
+                layout.reserve(height * LINE_SPACING + BODY_HEIGHT * LINE_SPACING * 2.0);
+                if *level == 1 {
+                    layout.section = text.clone();
+                    if layout.page().texts.is_empty() {
+                        layout.page().name = text.clone();
+                    }
+                }
+                layout.lines(text, height);
+            }
+            Element::Paragraph(text) => layout.lines(text, BODY_HEIGHT),
+            Element::Table { headers, rows } => layout.table(headers, rows),
+            Element::Image(image, caption) => layout.image(image, caption),
+            Element::PageBreak => {
+                if !layout.page().texts.is_empty() || !layout.page().images.is_empty() {
+                    layout.new_page();
+                }
+            }
+        }
+    }
+
+    let count = layout.pages.len();
+    for (i, page) in layout.pages.iter_mut().enumerate() {
+        page.texts.push(PlotText {
+            position: (PAGE_MARGIN, PAGE_MARGIN),
+            height: SMALL_HEIGHT,
+            rotation: 0.0,
+            text: format!("{} - Page {} of {}", title, i + 1, count),
+        });
+    }
+    write_pdf(&layout.pages, title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{
+        Color, Entity, GeometryType, Layer, Line, LineType, LineWeight, Vec3,
+    };
+    use crate::standards::LayerRule;
+
+    fn drawing(layer: &str) -> Document {
+        let mut doc = Document::new();
+        doc.metadata.author = "J. Okafor".to_string();
+        doc.add_layer(Layer {
+            name: layer.to_string(),
+            color: Color::white(),
+            line_type: LineType::Continuous,
+            line_weight: LineWeight::Width(35),
+            visible: true,
+            locked: false,
+            frozen: false,
+            plottable: true,
+            confidential: false,
+        });
+        doc.add_entity(Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::new(0.0, 0.0, 0.0),
+                end: Vec3::new(100.0, 50.0, 0.0),
+            }),
+            layer.to_string(),
+        ));
+        doc
+    }
+
+    fn set() -> SheetSet {
+        let mut set = SheetSet::new("Bus Depot");
+        set.set_field("CLIENT", "Transit Authority");
+        let arch = set.add_subset("Architectural", "A-");
+        arch.add_sheet(
+            Sheet::new("Plan", "plan.cdy")
+                .with_field("Revision", "C")
+                .with_field(FIELD_REVISION_CHANGES, "CR-002"),
+        );
+        arch.add_sheet(Sheet::new("Sections", "sections.cdy"));
+        arch.add_sheet(Sheet::new("Details", "missing.cdy"));
+        set
+    }
+
+    fn collect(options: &CollectOptions) -> ReportData {
+        ReportData::collect(&set(), options, |path| match path.to_str() {
+            Some("plan.cdy") => Ok(drawing("A-WALL")),
+            Some("sections.cdy") => Ok(drawing("walls")),
+            _ => Err(SheetError::Load {
+                path: path.to_path_buf(),
+                message: "not found".to_string(),
+            }),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_collect_project_data() {
+        let options = CollectOptions {
+            standard: Some(CadStandard::new("Office").with_layer(LayerRule::new("A-*"))),
+            image_width: 200,
+            ..Default::default()
+        };
+        let data = collect(&options);
+        assert_eq!(data.fields["SHEET_SET"], "Bus Depot");
+        assert_eq!(data.fields["CLIENT"], "Transit Authority");
+        assert_eq!(data.fields[FIELD_QA_RESULT], "FAIL");
+
+        let sheets = &data.tables[TABLE_SHEETS];
+        assert_eq!(sheets.len(), 3);
+        assert_eq!(sheets[0]["status"], "ok");
+        assert!(sheets[2]["status"].contains("not found"));
+
+        let revisions = &data.tables[TABLE_REVISIONS];
+        assert_eq!(revisions.len(), 2);
+        let plan = revisions.iter().find(|r| r["number"] == "A-001").unwrap();
+        assert_eq!(plan["revision"], "C");
+        assert_eq!(plan["author"], "J. Okafor");
+        assert_eq!(plan["changes"], "CR-002");
+
+        let summary = &data.tables[TABLE_QA_SUMMARY];
+        assert_eq!(summary[0]["result"], "PASS");
+        assert_eq!(summary[1]["result"], "FAIL");
+        assert!(data.tables[TABLE_QA].iter().all(|f| f["number"] == "A-002"));
+
+        // Extents plots take the shape of the A3 landscape paper
+        let image = &data.images["A-001"];
+        assert_eq!((image.width, image.height), (200, 141));
+        assert!(image.rgb.iter().any(|&c| c < 128));
+        assert!(!data.images.contains_key("A-003"));
+    }
+
+    #[test]
+    fn test_template_expansion() {
+        let mut data = ReportData::new();
+        data.set_field("PROJECT", "Depot");
+        for (number, status) in [("A-001", "ok"), ("A-002", "missing | gone")] {
+            data.push_row(
+                TABLE_SHEETS,
+                ReportRow::from([
+                    ("number".to_string(), number.to_string()),
+                    ("status".to_string(), status.to_string()),
+                ]),
+            );
+        }
+        let template = ReportTemplate::new("list", "{{PROJECT}} drawings")
+            .with_block(ReportBlock::Heading {
+                text: "{{project}} <list>".to_string(),
+                level: 2,
+            })
+            .with_block(ReportBlock::Table {
+                table: TABLE_SHEETS.to_string(),
+                columns: vec![
+                    ReportColumn::field("Sheet", "number"),
+                    ReportColumn {
+                        header: "Status".to_string(),
+                        value: "{{status}} ({{PROJECT}})".to_string(),
+                    },
+                ],
+                filter: BTreeMap::new(),
+                empty: String::new(),
+            })
+            .with_block(ReportBlock::Repeat {
+                table: TABLE_SHEETS.to_string(),
+                filter: BTreeMap::from([("STATUS".to_string(), "ok".to_string())]),
+                blocks: vec![ReportBlock::Paragraph {
+                    text: "{{number}} is ready\n{{unknown}}".to_string(),
+                }],
+            })
+            .with_block(ReportBlock::Table {
+                table: "nothing".to_string(),
+                columns: vec![ReportColumn::field("A", "a")],
+                filter: BTreeMap::new(),
+                empty: "No rows".to_string(),
+            });
+
+        let markdown =
+            String::from_utf8(template.render(&data, ReportFormat::Markdown).unwrap()).unwrap();
+        assert!(markdown.starts_with("<!-- Depot drawings -->"));
+        assert!(markdown.contains("## Depot <list>\n"));
+        assert!(markdown.contains("| A-002 | missing \\| gone (Depot) |"));
+        assert!(markdown.contains("A-001 is ready  \n{{unknown}}\n"));
+        assert!(!markdown.contains("A-002 is ready"));
+        assert!(markdown.contains("No rows"));
+
+        let html = String::from_utf8(template.render(&data, ReportFormat::Html).unwrap()).unwrap();
+        assert!(html.contains("<h2>Depot &lt;list&gt;</h2>"));
+        assert!(html.contains("<p>A-001 is ready<br>{{unknown}}</p>"));
+
+        let json = serde_json::to_string(&template).unwrap();
+        assert!(json.contains("\"kind\":\"repeat\""));
+        assert_eq!(
+            serde_json::from_str::<ReportTemplate>(&json).unwrap(),
+            template
+        );
+
+        let bad = ReportTemplate::new("bad", "Bad").with_block(ReportBlock::Heading {
+            text: "Deep".to_string(),
+            level: 4,
+        });
+        assert!(matches!(bad.validate(), Err(SheetError::Template(_))));
+    }
+
+    #[test]
+    fn test_project_documentation_formats() {
+        let options = CollectOptions {
+            image_width: 120,
+            ..Default::default()
+        };
+        let data = collect(&options);
+        let template = ReportTemplate::project_documentation();
+        assert!(template.uses_images());
+
+        let pdf = template.render(&data, ReportFormat::Pdf).unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        // Title page, then one page per sheet
+        assert_eq!(text.matches("/Type /Page ").count(), 4);
+        assert!(text.contains("(Bus Depot Project Documentation - Page 4 of 4)"));
+        assert!(text.contains("/Subtype /Image"));
+        assert!(text.contains("([No image for A-003])"));
+
+        let html = String::from_utf8(template.render(&data, ReportFormat::Html).unwrap()).unwrap();
+        assert_eq!(html.matches("src=\"data:image/png;base64,").count(), 2);
+        assert!(html.contains("<td>A-002</td><td>Sections</td>"));
+        assert!(html.contains("No standard was checked."));
+
+        let markdown =
+            String::from_utf8(template.render(&data, ReportFormat::Markdown).unwrap()).unwrap();
+        assert!(markdown.contains("![plan.cdy](data:image/png;base64,iVBORw0KGgo"));
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap("one two three four", 9),
+            vec!["one two", "three", "four"]
+        );
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("", 4), vec![""]);
+        assert_eq!(truncate("Foundations", 8), "Found...");
+    }
+}
@@ -0,0 +1,676 @@
+//! Sheet set model
+//!
+//! A sheet set is an ordered list of subsets (disciplines such as
+//! architectural or structural), each holding sheets. A sheet points at a
+//! drawing file and the area of it to plot. Numbers are assigned from the
+//! subset prefix and the sheet's position unless a sheet carries a manual
+//! number.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use super::plot::{PlotArea, PlotScale, PlotSettings};
+use super::{SheetError, SheetResult};
+use crate::io::document::{
+    Document, Entity, GeometryType, Line, PaperSize, Text, TextAlignment, Vec3,
+};
+
+/// Field holding the sheet number
+pub const FIELD_SHEET_NUMBER: &str = "SHEET_NUMBER";
+/// Field holding the sheet title
+pub const FIELD_SHEET_TITLE: &str = "SHEET_TITLE";
+/// Field holding the number of sheets in the set
+pub const FIELD_SHEET_COUNT: &str = "SHEET_COUNT";
+/// Field holding the set name
+pub const FIELD_SET_NAME: &str = "SHEET_SET";
+/// Field holding the subset name
+pub const FIELD_SUBSET: &str = "SUBSET";
+
+/// How sheet numbers are generated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SheetNumbering {
+    /// First number in each subset (or in the set)
+    pub start: u32,
+    /// Minimum digits, zero padded
+    pub digits: usize,
+    /// Restart the counter in every subset
+    pub restart_per_subset: bool,
+}
+
+impl Default for SheetNumbering {
+    fn default() -> Self {
+        Self {
+            start: 1,
+            digits: 3,
+            restart_per_subset: true,
+        }
+    }
+}
+
+/// Generated sheet index (drawing list)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexSheet {
+    /// Number shown on the index sheet
+    pub number: String,
+    /// Title of the index sheet
+    pub title: String,
+    /// Paper for the index
+    pub paper: PaperSize,
+    /// Text height in millimeters
+    pub text_height: f64,
+}
+
+impl Default for IndexSheet {
+    fn default() -> Self {
+        Self {
+            number: "G-000".to_string(),
+            title: "Sheet Index".to_string(),
+            paper: PaperSize::A3,
+            text_height: 3.5,
+        }
+    }
+}
+
+/// One sheet of the set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sheet {
+    /// Sheet identifier
+    pub id: Uuid,
+    /// Sheet title
+    pub title: String,
+    /// Drawing file, relative paths resolve against the set file
+    pub document: PathBuf,
+    /// Plot settings; `None` uses the set defaults
+    pub plot: Option<PlotSettings>,
+    /// Manual number, overrides automatic numbering
+    pub number: Option<String>,
+    /// Sheet-specific title block fields
+    pub fields: BTreeMap<String, String>,
+    /// Include in publish jobs
+    pub publish: bool,
+}
+
+impl Sheet {
+    /// Create a sheet plotting the drawing's extents
+    pub fn new(title: &str, document: impl Into<PathBuf>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            document: document.into(),
+            plot: None,
+            number: None,
+            fields: BTreeMap::new(),
+            publish: true,
+        }
+    }
+
+    /// Plot a named view instead of the extents
+    pub fn with_view(mut self, view: &str) -> Self {
+        let mut plot = self.plot.take().unwrap_or_default();
+        plot.area = PlotArea::View(view.to_string());
+        self.plot = Some(plot);
+        self
+    }
+
+    /// Use specific plot settings
+    pub fn with_plot(mut self, plot: PlotSettings) -> Self {
+        self.plot = Some(plot);
+        self
+    }
+
+    /// Set a manual sheet number
+    pub fn with_number(mut self, number: &str) -> Self {
+        self.number = Some(number.to_string());
+        self
+    }
+
+    /// Set a sheet-specific field
+    pub fn with_field(mut self, name: &str, value: &str) -> Self {
+        self.fields.insert(name.to_string(), value.to_string());
+        self
+    }
+}
+
+/// Group of sheets (usually one discipline)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SheetSubset {
+    /// Subset name
+    pub name: String,
+    /// Number prefix, e.g. `A-`
+    pub prefix: String,
+    /// Subset-wide fields
+    pub fields: BTreeMap<String, String>,
+    /// Sheets in order
+    pub sheets: Vec<Sheet>,
+}
+
+impl SheetSubset {
+    /// Create an empty subset
+    pub fn new(name: &str, prefix: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            prefix: prefix.to_string(),
+            fields: BTreeMap::new(),
+            sheets: Vec::new(),
+        }
+    }
+
+    /// Append a sheet and return its id
+    pub fn add_sheet(&mut self, sheet: Sheet) -> Uuid {
+        let id = sheet.id;
+        self.sheets.push(sheet);
+        id
+    }
+}
+
+/// Sheet with its resolved number
+#[derive(Debug, Clone)]
+pub struct NumberedSheet<'a> {
+    /// Subset the sheet belongs to
+    pub subset: &'a SheetSubset,
+    /// The sheet
+    pub sheet: &'a Sheet,
+    /// Manual or generated number
+    pub number: String,
+}
+
+/// Collection of sheets across drawings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SheetSet {
+    /// Set identifier
+    pub id: Uuid,
+    /// Set name (usually the project)
+    pub name: String,
+    /// Description
+    pub description: String,
+    /// Fields shared by every sheet's title block (project, client, ...)
+    pub fields: BTreeMap<String, String>,
+    /// Title block block name; its inserts receive the fields as attributes
+    pub title_block: Option<String>,
+    /// Numbering scheme
+    pub numbering: SheetNumbering,
+    /// Plot settings for sheets without their own
+    pub default_plot: PlotSettings,
+    /// Generated index sheet, published first
+    pub index: Option<IndexSheet>,
+    /// Subsets in order
+    pub subsets: Vec<SheetSubset>,
+}
+
+impl SheetSet {
+    /// Create an empty set
+    pub fn new(name: &str) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: String::new(),
+            fields: BTreeMap::new(),
+            title_block: None,
+            numbering: SheetNumbering::default(),
+            default_plot: PlotSettings::default(),
+            index: None,
+            subsets: Vec::new(),
+        }
+    }
+
+    /// Load a set from JSON
+    pub fn load(path: impl AsRef<Path>) -> SheetResult<Self> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Save the set as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> SheetResult<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Set a shared field
+    pub fn set_field(&mut self, name: &str, value: &str) {
+        self.fields.insert(name.to_string(), value.to_string());
+    }
+
+    /// Add a subset and return it for filling
+    pub fn add_subset(&mut self, name: &str, prefix: &str) -> &mut SheetSubset {
+        self.subsets.push(SheetSubset::new(name, prefix));
+        self.subsets.last_mut().unwrap()
+    }
+
+    /// Subset by name
+    pub fn subset_mut(&mut self, name: &str) -> Option<&mut SheetSubset> {
+        self.subsets.iter_mut().find(|s| s.name == name)
+    }
+
+    /// Sheet by id
+    pub fn sheet(&self, id: Uuid) -> Option<&Sheet> {
+        self.subsets
+            .iter()
+            .flat_map(|s| &s.sheets)
+            .find(|s| s.id == id)
+    }
+
+    /// Mutable sheet by id
+    pub fn sheet_mut(&mut self, id: Uuid) -> Option<&mut Sheet> {
+        self.subsets
+            .iter_mut()
+            .flat_map(|s| &mut s.sheets)
+            .find(|s| s.id == id)
+    }
+
+    /// Remove a sheet; numbers of the following sheets shift down
+    pub fn remove_sheet(&mut self, id: Uuid) -> Option<Sheet> {
+        self.subsets.iter_mut().find_map(|subset| {
+            let index = subset.sheets.iter().position(|s| s.id == id)?;
+            Some(subset.sheets.remove(index))
+        })
+    }
+
+    /// Move a sheet to `position` within the named subset
+    pub fn move_sheet(&mut self, id: Uuid, subset: &str, position: usize) -> SheetResult<()> {
+        let target = self
+            .subsets
+            .iter()
+            .position(|s| s.name == subset)
+            .ok_or_else(|| SheetError::NotFound(format!("subset '{}'", subset)))?;
+        let sheet = self
+            .subsets
+            .iter_mut()
+            .find_map(|s| {
+                let index = s.sheets.iter().position(|sh| sh.id == id)?;
+                Some(s.sheets.remove(index))
+            })
+            .ok_or_else(|| SheetError::NotFound(format!("sheet {}", id)))?;
+        let sheets = &mut self.subsets[target].sheets;
+        sheets.insert(position.min(sheets.len()), sheet);
+        Ok(())
+    }
+
+    /// Sheets in publish order with their numbers
+    ///
+    /// Numbers are derived from the current order every time, so edits to
+    /// subsets and sheets never leave stale numbers behind.
+    pub fn sheets(&self) -> Vec<NumberedSheet<'_>> {
+        let mut out = Vec::new();
+        let mut counter = self.numbering.start;
+        for subset in &self.subsets {
+            if self.numbering.restart_per_subset {
+                counter = self.numbering.start;
+            }
+            for sheet in &subset.sheets {
+                let number = match &sheet.number {
+                    Some(manual) => manual.clone(),
+                    None => format!(
+                        "{}{:0width$}",
+                        subset.prefix,
+                        counter,
+                        width = self.numbering.digits
+                    ),
+                };
+                out.push(NumberedSheet {
+                    subset,
+                    sheet,
+                    number,
+                });
+                counter += 1;
+            }
+        }
+        out
+    }
+
+    /// Number of a sheet
+    pub fn number_of(&self, id: Uuid) -> Option<String> {
+        self.sheets()
+            .into_iter()
+            .find(|s| s.sheet.id == id)
+            .map(|s| s.number)
+    }
+
+    /// Check that every number is unique
+    pub fn validate(&self) -> SheetResult<()> {
+        let mut seen = HashSet::new();
+        if let Some(index) = &self.index {
+            seen.insert(index.number.clone());
+        }
+        for entry in self.sheets() {
+            if !seen.insert(entry.number.clone()) {
+                return Err(SheetError::DuplicateNumber(entry.number));
+            }
+        }
+        Ok(())
+    }
+
+    /// Title block fields for a sheet
+    ///
+    /// Sheet fields override subset fields, which override set fields. The
+    /// automatic fields (number, title, count, set, subset) always win.
+    pub fn fields_for(&self, id: Uuid) -> SheetResult<BTreeMap<String, String>> {
+        let mut fields = self.fields.clone();
+        let sheets = self.sheets();
+        let count = sheets.len() + usize::from(self.index.is_some());
+        let entry = sheets
+            .into_iter()
+            .find(|s| s.sheet.id == id)
+            .ok_or_else(|| SheetError::NotFound(format!("sheet {}", id)))?;
+
+        fields.extend(entry.subset.fields.clone());
+        fields.extend(entry.sheet.fields.clone());
+        fields.insert(FIELD_SHEET_NUMBER.to_string(), entry.number);
+        fields.insert(FIELD_SHEET_TITLE.to_string(), entry.sheet.title.clone());
+        fields.insert(FIELD_SHEET_COUNT.to_string(), count.to_string());
+        fields.insert(FIELD_SET_NAME.to_string(), self.name.clone());
+        fields.insert(FIELD_SUBSET.to_string(), entry.subset.name.clone());
+        Ok(fields)
+    }
+
+    /// Plot settings for a sheet
+    pub fn plot_settings(&self, sheet: &Sheet) -> PlotSettings {
+        sheet
+            .plot
+            .clone()
+            .unwrap_or_else(|| self.default_plot.clone())
+    }
+
+    /// Build the index sheet drawing, or `None` if the set has no index
+    ///
+    /// The drawing is laid out in paper millimeters and plots at 1:1.
+    pub fn index_document(&self) -> Option<(Document, PlotSettings)> {
+        let index = self.index.clone()?;
+        let (width, height) = index.paper.dimensions_mm();
+        let (width, height) = (width.max(height), width.min(height));
+        let margin = 15.0;
+        let row = index.text_height * 2.0;
+        let number_col = margin;
+        let title_col = margin + index.text_height * 12.0;
+
+        let mut doc = Document::new();
+        doc.metadata.title = format!("{} - {}", self.name, index.title);
+        let mut y = height - margin;
+        let text = |doc: &mut Document, x: f64, y: f64, h: f64, s: &str| {
+            doc.add_entity(Entity::new(
+                GeometryType::Text(Text {
+                    position: Vec3::new(x, y, 0.0),
+                    text: s.to_string(),
+                    height: h,
+                    rotation: 0.0,
+                    style: "Standard".to_string(),
+                    horizontal_alignment: TextAlignment::Left,
+                    vertical_alignment: TextAlignment::Bottom,
+                }),
+                "0".to_string(),
+            ));
+        };
+
+        text(&mut doc, margin, y, index.text_height * 1.5, &index.title);
+        y -= row * 1.5;
+        text(&mut doc, number_col, y, index.text_height, "Number");
+        text(&mut doc, title_col, y, index.text_height, "Title");
+        y -= row * 0.4;
+        doc.add_entity(Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::new(margin, y, 0.0),
+                end: Vec3::new(width - margin, y, 0.0),
+            }),
+            "0".to_string(),
+        ));
+        y -= row;
+
+        let mut rows = vec![(index.number.clone(), index.title.clone())];
+        let mut last_subset: Option<String> = None;
+        for entry in self.sheets() {
+            if !entry.sheet.publish {
+                continue;
+            }
+            if last_subset.as_deref() != Some(entry.subset.name.as_str()) {
+                rows.push((String::new(), entry.subset.name.to_uppercase()));
+                last_subset = Some(entry.subset.name.clone());
+            }
+            rows.push((entry.number, entry.sheet.title.clone()));
+        }
+        for (number, title) in rows {
+            if !number.is_empty() {
+                text(&mut doc, number_col, y, index.text_height, &number);
+            }
+            text(&mut doc, title_col, y, index.text_height, &title);
+            y -= row;
+        }
+
+        let plot = PlotSettings {
+            paper: index.paper,
+            area: PlotArea::Window {
+                min: (0.0, 0.0),
+                max: (width, height),
+            },
+            scale: PlotScale::Ratio(1.0),
+            margin: 0.0,
+            ..PlotSettings::default()
+        };
+        Some((doc, plot))
+    }
+}
+
+/// Write title block fields into a drawing
+///
+/// Inserts of `title_block` get their attributes set from the fields
+/// (attribute tags match field names case-insensitively), and `{{FIELD}}`
+/// placeholders in text anywhere in the drawing or its blocks are replaced.
+/// Returns the number of entities changed.
+pub fn fill_title_block(
+    doc: &mut Document,
+    title_block: Option<&str>,
+    fields: &BTreeMap<String, String>,
+) -> usize {
+    let lookup = |tag: &str| {
+        fields
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(tag))
+            .map(|(_, v)| v.clone())
+    };
+
+    let mut changed = 0;
+    let blocks = doc.blocks.values_mut().flat_map(|b| &mut b.entities);
+    for entity in doc.entities.iter_mut().chain(blocks) {
+        let hit = match &mut entity.geometry {
+            GeometryType::Insert(insert) if Some(insert.block_name.as_str()) == title_block => {
+                let mut hit = false;
+                for (tag, value) in insert.attributes.iter_mut() {
+                    if let Some(field) = lookup(tag) {
+                        *value = field;
+                        hit = true;
+                    }
+                }
+                hit
+            }
+            GeometryType::Text(t) => substitute(&mut t.text, fields),
+            GeometryType::MText(t) => substitute(&mut t.text, fields),
+            _ => false,
+        };
+        changed += usize::from(hit);
+    }
+    changed
+}
+
+pub(super) fn substitute(text: &mut String, fields: &BTreeMap<String, String>) -> bool {
+    if !text.contains("{{") {
+        return false;
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    let mut hit = false;
+    while let Some(open) = rest.find("{{") {
+        let Some(close) = rest[open..].find("}}") else {
+            break;
+        };
+        let name = rest[open + 2..open + close].trim();
+        out.push_str(&rest[..open]);
+        match fields.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)) {
+            Some((_, value)) => {
+                out.push_str(value);
+                hit = true;
+            }
+            None => out.push_str(&rest[open..open + close + 2]),
+        }
+        rest = &rest[open + close + 2..];
+    }
+    out.push_str(rest);
+    *text = out;
+    hit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{Block, Insert};
+    use std::collections::HashMap;
+
+    fn sample_set() -> (SheetSet, Vec<Uuid>) {
+        let mut set = SheetSet::new("Clinic");
+        set.set_field("PROJECT", "Riverside Clinic");
+        set.set_field("CLIENT", "Health Trust");
+        let arch = set.add_subset("Architectural", "A-");
+        let a1 = arch.add_sheet(Sheet::new("Ground Floor Plan", "plans.cdy").with_view("GF"));
+        let a2 = arch.add_sheet(Sheet::new("First Floor Plan", "plans.cdy").with_view("FF"));
+        let structural = set.add_subset("Structural", "S-");
+        structural
+            .fields
+            .insert("CLIENT".to_string(), "Engineer".to_string());
+        let s1 = structural.add_sheet(Sheet::new("Foundations", "structure.cdy"));
+        (set, vec![a1, a2, s1])
+    }
+
+    #[test]
+    fn test_numbering() {
+        let (mut set, ids) = sample_set();
+        assert_eq!(set.number_of(ids[0]).unwrap(), "A-001");
+        assert_eq!(set.number_of(ids[1]).unwrap(), "A-002");
+        assert_eq!(set.number_of(ids[2]).unwrap(), "S-001");
+
+        set.move_sheet(ids[1], "Architectural", 0).unwrap();
+        assert_eq!(set.number_of(ids[1]).unwrap(), "A-001");
+        assert_eq!(set.number_of(ids[0]).unwrap(), "A-002");
+
+        set.numbering.restart_per_subset = false;
+        assert_eq!(set.number_of(ids[2]).unwrap(), "S-003");
+
+        set.sheet_mut(ids[2]).unwrap().number = Some("A-001".to_string());
+        assert!(matches!(
+            set.validate(),
+            Err(SheetError::DuplicateNumber(n)) if n == "A-001"
+        ));
+
+        set.remove_sheet(ids[1]);
+        assert_eq!(set.number_of(ids[0]).unwrap(), "A-001");
+    }
+
+    #[test]
+    fn test_new_sheets_are_numbered() {
+        let (mut set, _) = sample_set();
+        let id = set
+            .subset_mut("Structural")
+            .unwrap()
+            .add_sheet(Sheet::new("Roof Framing", "structure.cdy"));
+        assert_eq!(set.number_of(id).unwrap(), "S-002");
+    }
+
+    #[test]
+    fn test_fields_precedence() {
+        let (mut set, ids) = sample_set();
+        set.sheet_mut(ids[2])
+            .unwrap()
+            .fields
+            .insert(FIELD_SHEET_NUMBER.to_string(), "ignored".to_string());
+        set.index = Some(IndexSheet::default());
+
+        let fields = set.fields_for(ids[2]).unwrap();
+        assert_eq!(fields["PROJECT"], "Riverside Clinic");
+        assert_eq!(fields["CLIENT"], "Engineer");
+        assert_eq!(fields[FIELD_SHEET_NUMBER], "S-001");
+        assert_eq!(fields[FIELD_SHEET_COUNT], "4");
+        assert_eq!(fields[FIELD_SUBSET], "Structural");
+        assert!(set.fields_for(Uuid::new_v4()).is_err());
+    }
+
+    #[test]
+    fn test_fill_title_block() {
+        let mut doc = Document::new();
+        doc.add_block(Block {
+            name: "TB".to_string(),
+            base_point: Vec3::zero(),
+            entities: vec![Entity::new(
+                GeometryType::Text(Text {
+                    position: Vec3::zero(),
+                    text: "Sheet {{sheet_number}} of {{SHEET_COUNT}} {{UNKNOWN}}".to_string(),
+                    height: 2.5,
+                    rotation: 0.0,
+                    style: "Standard".to_string(),
+                    horizontal_alignment: TextAlignment::Left,
+                    vertical_alignment: TextAlignment::Bottom,
+                }),
+                "0".to_string(),
+            )],
+            description: String::new(),
+        });
+        let mut attributes = HashMap::new();
+        attributes.insert("project".to_string(), String::new());
+        attributes.insert("REV".to_string(), "B".to_string());
+        doc.add_entity(Entity::new(
+            GeometryType::Insert(Insert {
+                block_name: "TB".to_string(),
+                position: Vec3::zero(),
+                scale: Vec3::new(1.0, 1.0, 1.0),
+                rotation: 0.0,
+                attributes,
+            }),
+            "0".to_string(),
+        ));
+
+        let (set, ids) = sample_set();
+        let fields = set.fields_for(ids[0]).unwrap();
+        assert_eq!(fill_title_block(&mut doc, Some("TB"), &fields), 2);
+
+        let GeometryType::Insert(insert) = &doc.entities[0].geometry else {
+            panic!("expected insert");
+        };
+        assert_eq!(insert.attributes["project"], "Riverside Clinic");
+        assert_eq!(insert.attributes["REV"], "B");
+        let GeometryType::Text(text) = &doc.blocks["TB"].entities[0].geometry else {
+            panic!("expected text");
+        };
+        assert_eq!(text.text, "Sheet A-001 of 3 {{UNKNOWN}}");
+    }
+
+    #[test]
+    fn test_index_document() {
+        let (mut set, _) = sample_set();
+        assert!(set.index_document().is_none());
+
+        set.index = Some(IndexSheet::default());
+        let (doc, plot) = set.index_document().unwrap();
+        let texts: Vec<&str> = doc
+            .entities
+            .iter()
+            .filter_map(|e| match &e.geometry {
+                GeometryType::Text(t) => Some(t.text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(texts.contains(&"G-000"));
+        assert!(texts.contains(&"A-002"));
+        assert!(texts.contains(&"Foundations"));
+        assert!(texts.contains(&"STRUCTURAL"));
+        assert_eq!(plot.scale, PlotScale::Ratio(1.0));
+    }
+
+    #[test]
+    fn test_save_load_roundtrip() {
+        let (set, ids) = sample_set();
+        let path = std::env::temp_dir().join(format!("sheetset-{}.json", Uuid::new_v4()));
+        set.save(&path).unwrap();
+        let loaded = SheetSet::load(&path).unwrap();
+        fs::remove_file(&path).ok();
+        assert_eq!(loaded.name, "Clinic");
+        assert_eq!(loaded.number_of(ids[2]).unwrap(), "S-001");
+    }
+}
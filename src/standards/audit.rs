@@ -0,0 +1,658 @@
+//! Auditing documents against a standard
+//!
+//! [`StandardsChecker::check`] never stops at the first problem: every
+//! finding is collected so a deliverable can be fixed in one pass.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use uuid::Uuid;
+
+use super::standard::CadStandard;
+use super::StandardsResult;
+use crate::io::document::{Color, Document, Entity, GeometryType, LineType, LineWeight};
+
+/// Layers every drawing has, exempt from the layer name check
+const SYSTEM_LAYERS: &[&str] = &["0", "DEFPOINTS"];
+
+/// Rule a finding comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RuleKind {
+    /// Drawing units
+    Units,
+    /// Layer name not covered by any layer rule
+    LayerName,
+    /// Layer color, linetype, lineweight, or plot setting
+    LayerProperty,
+    /// Required layer absent
+    RequiredLayer,
+    /// Linetype not approved
+    LineType,
+    /// Text height not approved
+    TextHeight,
+    /// Text style not approved
+    TextStyle,
+    /// Title block missing or incomplete
+    TitleBlock,
+}
+
+impl RuleKind {
+    /// Stable identifier used in reports and CI output
+    pub fn id(&self) -> &'static str {
+        match self {
+            RuleKind::Units => "units",
+            RuleKind::LayerName => "layer-name",
+            RuleKind::LayerProperty => "layer-property",
+            RuleKind::RequiredLayer => "required-layer",
+            RuleKind::LineType => "linetype",
+            RuleKind::TextHeight => "text-height",
+            RuleKind::TextStyle => "text-style",
+            RuleKind::TitleBlock => "title-block",
+        }
+    }
+}
+
+/// Finding severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    /// Reported, but the deliverable still passes
+    Warning,
+    /// The deliverable fails
+    Error,
+}
+
+/// One departure from the standard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Finding {
+    /// Rule broken
+    pub rule: RuleKind,
+    /// Severity
+    pub severity: Severity,
+    /// Description
+    pub message: String,
+    /// Layer concerned
+    pub layer: Option<String>,
+    /// Entity concerned
+    pub entity: Option<Uuid>,
+}
+
+/// Result of auditing one document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StandardsReport {
+    /// Standard name
+    pub standard: String,
+    /// Document title
+    pub document: String,
+    /// Layers examined
+    pub layers_checked: usize,
+    /// Entities examined
+    pub entities_checked: usize,
+    /// Findings, in rule order
+    pub findings: Vec<Finding>,
+}
+
+impl StandardsReport {
+    /// Whether the document meets the standard (warnings allowed)
+    pub fn passed(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Findings that fail the document
+    pub fn errors(&self) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == Severity::Error)
+    }
+
+    /// Findings reported as warnings
+    pub fn warnings(&self) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == Severity::Warning)
+    }
+
+    /// Findings from one rule
+    pub fn findings_for(&self, rule: RuleKind) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(move |f| f.rule == rule)
+    }
+
+    /// One-line outcome
+    pub fn summary(&self) -> String {
+        format!(
+            "{}: {} error(s), {} warning(s) against {}",
+            if self.passed() { "PASS" } else { "FAIL" },
+            self.errors().count(),
+            self.warnings().count(),
+            self.standard
+        )
+    }
+
+    /// Plain-text report
+    pub fn to_text(&self) -> String {
+        let mut s = String::new();
+        let _ = writeln!(s, "STANDARDS AUDIT: {}", self.document);
+        let _ = writeln!(s, "{}", self.summary());
+        let _ = writeln!(
+            s,
+            "Checked {} layer(s), {} entity(ies)",
+            self.layers_checked, self.entities_checked
+        );
+        if !self.findings.is_empty() {
+            s.push('\n');
+        }
+        for finding in &self.findings {
+            let severity = match finding.severity {
+                Severity::Error => "ERROR",
+                Severity::Warning => "WARNING",
+            };
+            let _ = write!(
+                s,
+                "{:<8} {:<15} {}",
+                severity,
+                finding.rule.id(),
+                finding.message
+            );
+            if let Some(entity) = finding.entity {
+                let _ = write!(s, " [{}]", entity);
+            }
+            s.push('\n');
+        }
+        s
+    }
+}
+
+/// Audits documents against one standard
+#[derive(Debug, Clone)]
+pub struct StandardsChecker {
+    standard: CadStandard,
+}
+
+impl StandardsChecker {
+    /// Create a checker, rejecting standards that cannot be applied
+    pub fn new(standard: CadStandard) -> StandardsResult<Self> {
+        standard.validate()?;
+        Ok(Self { standard })
+    }
+
+    /// The standard being applied
+    pub fn standard(&self) -> &CadStandard {
+        &self.standard
+    }
+
+    /// Audit a document
+    pub fn check(&self, doc: &Document) -> StandardsReport {
+        let mut audit = Audit {
+            standard: &self.standard,
+            findings: Vec::new(),
+        };
+        audit.units(doc);
+        audit.layers(doc);
+        audit.entities(doc);
+        audit.title_block(doc);
+
+        let mut findings = audit.findings;
+        findings.sort_by_key(|f| f.rule);
+        StandardsReport {
+            standard: self.standard.name.clone(),
+            document: doc.metadata.title.clone(),
+            layers_checked: doc.layers.len(),
+            entities_checked: doc.entities.len(),
+            findings,
+        }
+    }
+}
+
+struct Audit<'a> {
+    standard: &'a CadStandard,
+    findings: Vec<Finding>,
+}
+
+impl Audit<'_> {
+    fn report(
+        &mut self,
+        rule: RuleKind,
+        message: String,
+        layer: Option<&str>,
+        entity: Option<Uuid>,
+    ) {
+        let severity = if self.standard.warnings.contains(&rule) {
+            Severity::Warning
+        } else {
+            Severity::Error
+        };
+        self.findings.push(Finding {
+            rule,
+            severity,
+            message,
+            layer: layer.map(str::to_string),
+            entity,
+        });
+    }
+
+    fn units(&mut self, doc: &Document) {
+        if let Some(units) = self.standard.units {
+            if doc.settings.units != units {
+                self.report(
+                    RuleKind::Units,
+                    format!(
+                        "Drawing units are {:?}; standard requires {:?}",
+                        doc.settings.units, units
+                    ),
+                    None,
+                    None,
+                );
+            }
+        }
+    }
+
+    fn layers(&mut self, doc: &Document) {
+        let mut layers: Vec<_> = doc.layers.values().collect();
+        layers.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for layer in &layers {
+            let name = layer.name.as_str();
+            let Some(rule) = self.standard.layer_rule(name) else {
+                let system = SYSTEM_LAYERS.iter().any(|s| s.eq_ignore_ascii_case(name));
+                if !system && !self.standard.allow_other_layers {
+                    self.report(
+                        RuleKind::LayerName,
+                        format!("Layer '{}' does not match any layer rule", name),
+                        Some(name),
+                        None,
+                    );
+                }
+                self.line_type(
+                    &layer.line_type,
+                    format!("Layer '{}'", name),
+                    Some(name),
+                    None,
+                );
+                continue;
+            };
+
+            let mut mismatches = Vec::new();
+            if let Some(color) = rule.color.filter(|c| *c != layer.color) {
+                mismatches.push(format!(
+                    "color is {}, expected {}",
+                    color_name(&layer.color),
+                    color_name(&color)
+                ));
+            }
+            if let Some(expected) = &rule.line_type {
+                let actual = line_type_name(&layer.line_type);
+                if !actual.eq_ignore_ascii_case(expected) {
+                    mismatches.push(format!("linetype is {}, expected {}", actual, expected));
+                }
+            }
+            if let Some(weight) = rule.line_weight.filter(|w| *w != layer.line_weight) {
+                mismatches.push(format!(
+                    "lineweight is {}, expected {}",
+                    weight_name(&layer.line_weight),
+                    weight_name(&weight)
+                ));
+            }
+            if let Some(plottable) = rule.plottable.filter(|p| *p != layer.plottable) {
+                mismatches.push(
+                    if plottable {
+                        "does not plot"
+                    } else {
+                        "plots but must not"
+                    }
+                    .to_string(),
+                );
+            }
+            for mismatch in mismatches {
+                self.report(
+                    RuleKind::LayerProperty,
+                    format!("Layer '{}' {} (rule {})", name, mismatch, rule.pattern),
+                    Some(name),
+                    None,
+                );
+            }
+            self.line_type(
+                &layer.line_type,
+                format!("Layer '{}'", name),
+                Some(name),
+                None,
+            );
+        }
+
+        for rule in self.standard.layers.iter().filter(|r| r.required) {
+            if !layers.iter().any(|l| rule.matches(&l.name)) {
+                self.report(
+                    RuleKind::RequiredLayer,
+                    format!("No layer matches required pattern '{}'", rule.pattern),
+                    None,
+                    None,
+                );
+            }
+        }
+    }
+
+    fn line_type(
+        &mut self,
+        line_type: &LineType,
+        owner: String,
+        layer: Option<&str>,
+        entity: Option<Uuid>,
+    ) {
+        let approved = &self.standard.line_types;
+        let name = line_type_name(line_type);
+        if !approved.is_empty() && !approved.iter().any(|a| a.eq_ignore_ascii_case(name)) {
+            self.report(
+                RuleKind::LineType,
+                format!("{} uses linetype {}, which is not approved", owner, name),
+                layer,
+                entity,
+            );
+        }
+    }
+
+    fn entities(&mut self, doc: &Document) {
+        for entity in &doc.entities {
+            if let Some(line_type) = &entity.line_type {
+                let owner = format!("{} on '{}'", entity.geometry.type_name(), entity.layer);
+                self.line_type(line_type, owner, Some(&entity.layer), Some(entity.id));
+            }
+            let (height, style) = match &entity.geometry {
+                GeometryType::Text(text) => (text.height, &text.style),
+                GeometryType::MText(text) => (text.height, &text.style),
+                _ => continue,
+            };
+            self.text(entity, height, style);
+        }
+    }
+
+    fn text(&mut self, entity: &Entity, height: f64, style: &str) {
+        let Some(rule) = &self.standard.text else {
+            return;
+        };
+        let height_ok = rule.allows_height(height);
+        let style_ok = rule.allows_style(style);
+        let layer = Some(entity.layer.as_str());
+        if !height_ok {
+            let approved: Vec<String> = rule.heights.iter().map(|h| h.to_string()).collect();
+            self.report(
+                RuleKind::TextHeight,
+                format!(
+                    "Text height {} on '{}' is not one of {}",
+                    height,
+                    entity.layer,
+                    approved.join(", ")
+                ),
+                layer,
+                Some(entity.id),
+            );
+        }
+        if !style_ok {
+            self.report(
+                RuleKind::TextStyle,
+                format!(
+                    "Text style '{}' on '{}' is not approved",
+                    style, entity.layer
+                ),
+                layer,
+                Some(entity.id),
+            );
+        }
+    }
+
+    fn title_block(&mut self, doc: &Document) {
+        let Some(rule) = &self.standard.title_block else {
+            return;
+        };
+        let inserts: Vec<_> = doc
+            .entities
+            .iter()
+            .filter_map(|e| match &e.geometry {
+                GeometryType::Insert(insert)
+                    if insert.block_name.eq_ignore_ascii_case(&rule.block) =>
+                {
+                    Some((e, insert))
+                }
+                _ => None,
+            })
+            .collect();
+
+        if inserts.is_empty() {
+            self.report(
+                RuleKind::TitleBlock,
+                format!("Title block '{}' is not inserted", rule.block),
+                None,
+                None,
+            );
+        }
+        for (entity, insert) in inserts {
+            for field in &rule.fields {
+                let filled = insert.attributes.iter().any(|(tag, value)| {
+                    tag.eq_ignore_ascii_case(field) && !value.trim().is_empty()
+                });
+                if !filled {
+                    self.report(
+                        RuleKind::TitleBlock,
+                        format!("Title block field {} is empty", field),
+                        Some(&entity.layer),
+                        Some(entity.id),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Linetype name as written in standards
+pub fn line_type_name(line_type: &LineType) -> &str {
+    match line_type {
+        LineType::Continuous => "Continuous",
+        LineType::Dashed => "Dashed",
+        LineType::Dotted => "Dotted",
+        LineType::DashDot => "DashDot",
+        LineType::Custom { name, .. } => name,
+    }
+}
+
+fn color_name(color: &Color) -> String {
+    format!("{},{},{}", color.r, color.g, color.b)
+}
+
+fn weight_name(weight: &LineWeight) -> String {
+    match weight {
+        LineWeight::Width(w) => format!("{:.2} mm", *w as f64 / 100.0),
+        other => format!("{:?}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{Insert, Layer, Line, MText, Text, TextAlignment, Vec3};
+    use crate::io::units::Unit;
+    use crate::standards::standard::{LayerRule, TextRule};
+    use std::collections::HashMap;
+
+    fn layer(name: &str, color: Color, line_type: LineType) -> Layer {
+        Layer {
+            name: name.to_string(),
+            color,
+            line_type,
+            line_weight: LineWeight::Width(35),
+            visible: true,
+            locked: false,
+            frozen: false,
+            plottable: true,
+            confidential: false,
+        }
+    }
+
+    fn text(layer: &str, height: f64, style: &str) -> Entity {
+        Entity::new(
+            GeometryType::Text(Text {
+                position: Vec3::zero(),
+                text: "NOTE".to_string(),
+                height,
+                rotation: 0.0,
+                style: style.to_string(),
+                horizontal_alignment: TextAlignment::Left,
+                vertical_alignment: TextAlignment::Bottom,
+            }),
+            layer.to_string(),
+        )
+    }
+
+    fn title_block(fields: &[(&str, &str)]) -> Entity {
+        Entity::new(
+            GeometryType::Insert(Insert {
+                block_name: "TITLE_A1".to_string(),
+                position: Vec3::zero(),
+                scale: Vec3::new(1.0, 1.0, 1.0),
+                rotation: 0.0,
+                attributes: fields
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect::<HashMap<_, _>>(),
+            }),
+            "G-ANNO-TTLB".to_string(),
+        )
+    }
+
+    fn standard() -> CadStandard {
+        CadStandard::new("Client Standard v3")
+            .with_units(Unit::Millimeters)
+            .with_layer(
+                LayerRule::new("A-WALL*")
+                    .with_color(Color::new(255, 255, 0))
+                    .with_line_weight(LineWeight::Width(50)),
+            )
+            .with_layer(LayerRule::new("A-*"))
+            .with_layer(LayerRule::new("G-ANNO-*").required())
+            .with_layer(
+                LayerRule::new("S-GRID")
+                    .with_line_type("DashDot")
+                    .required(),
+            )
+            .with_line_type("Continuous")
+            .with_line_type("DashDot")
+            .with_text(TextRule {
+                heights: vec![2.5, 3.5, 5.0],
+                tolerance: 0.01,
+                styles: vec!["ISOCP".to_string()],
+            })
+            .with_title_block("TITLE_A1", &["SHEET_NUMBER", "TITLE", "CHECKED_BY"])
+            .with_warning(RuleKind::TextStyle)
+    }
+
+    fn compliant() -> Document {
+        let mut doc = Document::new();
+        doc.metadata.title = "Level 1 Plan".to_string();
+        let mut wall = layer("A-WALL", Color::new(255, 255, 0), LineType::Continuous);
+        wall.line_weight = LineWeight::Width(50);
+        doc.add_layer(wall);
+        doc.add_layer(layer("G-ANNO-TTLB", Color::white(), LineType::Continuous));
+        doc.add_entity(text("A-WALL", 2.5, "isocp"));
+        doc.add_entity(title_block(&[
+            ("SHEET_NUMBER", "A-101"),
+            ("title", "Level 1 Plan"),
+            ("CHECKED_BY", "JM"),
+        ]));
+        doc
+    }
+
+    #[test]
+    fn test_compliant_document_passes() {
+        let mut standard = standard();
+        standard.layers.pop();
+        let report = StandardsChecker::new(standard).unwrap().check(&compliant());
+        assert!(report.passed(), "{}", report.to_text());
+        assert!(report.findings.is_empty());
+        assert_eq!(report.layers_checked, 3);
+        assert_eq!(report.entities_checked, 2);
+        assert!(report.summary().starts_with("PASS"));
+    }
+
+    #[test]
+    fn test_findings() {
+        let mut doc = compliant();
+        doc.settings.units = Unit::Inches;
+        doc.add_layer(layer("A-WALL-EXT", Color::new(255, 0, 0), LineType::Dashed));
+        doc.add_layer(layer("walls", Color::white(), LineType::Continuous));
+        doc.add_entity(text("A-WALL", 2.0, "Standard"));
+        doc.add_entity(Entity::new(
+            GeometryType::MText(MText {
+                position: Vec3::zero(),
+                text: "GENERAL NOTES".to_string(),
+                height: 3.5,
+                width: 100.0,
+                rotation: 0.0,
+                style: "ISOCP".to_string(),
+                line_spacing: 1.0,
+            }),
+            "G-ANNO-NOTE".to_string(),
+        ));
+        let mut hidden = Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::zero(),
+                end: Vec3::new(1.0, 0.0, 0.0),
+            }),
+            "A-WALL".to_string(),
+        );
+        hidden.line_type = Some(LineType::Dotted);
+        doc.add_entity(hidden);
+        doc.entities[1] = title_block(&[("SHEET_NUMBER", "A-101"), ("TITLE", " ")]);
+
+        let report = StandardsChecker::new(standard()).unwrap().check(&doc);
+        let count = |rule| report.findings_for(rule).count();
+        assert_eq!(count(RuleKind::Units), 1);
+        assert_eq!(count(RuleKind::LayerName), 1);
+        // A-WALL-EXT: wrong color and lineweight
+        assert_eq!(count(RuleKind::LayerProperty), 2);
+        // S-GRID absent
+        assert_eq!(count(RuleKind::RequiredLayer), 1);
+        // Dashed layer, dotted entity override
+        assert_eq!(count(RuleKind::LineType), 2);
+        assert_eq!(count(RuleKind::TextHeight), 1);
+        assert_eq!(count(RuleKind::TextStyle), 1);
+        // TITLE blank, CHECKED_BY missing
+        assert_eq!(count(RuleKind::TitleBlock), 2);
+
+        assert!(!report.passed());
+        assert_eq!(report.warnings().count(), 1);
+        assert_eq!(report.errors().count(), 10);
+        assert!(report.findings.windows(2).all(|w| w[0].rule <= w[1].rule));
+
+        let name = report.findings_for(RuleKind::LayerName).next().unwrap();
+        assert_eq!(name.layer.as_deref(), Some("walls"));
+        let color = report.findings_for(RuleKind::LayerProperty).next().unwrap();
+        assert_eq!(
+            color.message,
+            "Layer 'A-WALL-EXT' color is 255,0,0, expected 255,255,0 (rule A-WALL*)"
+        );
+
+        let text = report.to_text();
+        assert!(text.starts_with("STANDARDS AUDIT: Level 1 Plan\nFAIL: 10 error(s), 1 warning(s)"));
+        assert!(text.contains("WARNING  text-style"));
+    }
+
+    #[test]
+    fn test_missing_title_block_and_other_layers() {
+        let mut standard = CadStandard::new("Loose").with_title_block("TB", &["TITLE"]);
+        standard.allow_other_layers = true;
+        let mut doc = Document::new();
+        doc.add_layer(layer("anything", Color::white(), LineType::Dashed));
+
+        let report = StandardsChecker::new(standard).unwrap().check(&doc);
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(
+            report.findings[0].message,
+            "Title block 'TB' is not inserted"
+        );
+    }
+
+    #[test]
+    fn test_report_json() {
+        let report = StandardsChecker::new(standard())
+            .unwrap()
+            .check(&Document::new());
+        let json = serde_json::to_string(&report).unwrap();
+        let back: StandardsReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, report);
+    }
+}
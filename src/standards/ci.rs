@@ -0,0 +1,119 @@
+//! CI output
+//!
+//! Converts a [`StandardsReport`] into the [`CheckResult`] every CI
+//! integration consumes, so a standards audit can gate a pull request or
+//! pipeline like any other check and be written as JUnit, SARIF, or platform
+//! annotations by the integrations CLI formatter.
+
+use std::collections::HashMap;
+
+use super::audit::{Severity, StandardsReport};
+use crate::integrations::{Annotation, AnnotationLevel, CheckResult, CheckStatus};
+
+impl StandardsReport {
+    /// Convert to a CI check result
+    ///
+    /// `path` is the drawing file as the repository knows it. Drawings have
+    /// no line numbers, so every annotation points at line 1 and carries the
+    /// layer and entity in its details.
+    pub fn to_check_result(&self, path: &str) -> CheckResult {
+        let status = if !self.passed() {
+            CheckStatus::Failure
+        } else if self.warnings().next().is_some() {
+            CheckStatus::Warning
+        } else {
+            CheckStatus::Success
+        };
+
+        let annotations = self
+            .findings
+            .iter()
+            .map(|finding| {
+                let mut details = Vec::new();
+                if let Some(layer) = &finding.layer {
+                    details.push(format!("layer: {}", layer));
+                }
+                if let Some(entity) = finding.entity {
+                    details.push(format!("entity: {}", entity));
+                }
+                Annotation {
+                    path: path.to_string(),
+                    start_line: 1,
+                    end_line: 1,
+                    start_column: None,
+                    end_column: None,
+                    level: match finding.severity {
+                        Severity::Error => AnnotationLevel::Error,
+                        Severity::Warning => AnnotationLevel::Warning,
+                    },
+                    message: finding.message.clone(),
+                    title: Some(format!("standards/{}", finding.rule.id())),
+                    raw_details: (!details.is_empty()).then(|| details.join("\n")),
+                }
+            })
+            .collect();
+
+        let mut metadata = HashMap::new();
+        metadata.insert("standard".to_string(), self.standard.clone());
+        metadata.insert("document".to_string(), self.document.clone());
+        metadata.insert(
+            "layers_checked".to_string(),
+            self.layers_checked.to_string(),
+        );
+        metadata.insert(
+            "entities_checked".to_string(),
+            self.entities_checked.to_string(),
+        );
+
+        CheckResult {
+            status,
+            summary: self.summary(),
+            details: Some(self.to_text()),
+            annotations,
+            execution_time_ms: 0,
+            metadata,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::Document;
+    use crate::standards::audit::{RuleKind, StandardsChecker};
+    use crate::standards::standard::CadStandard;
+
+    #[test]
+    fn test_check_result() {
+        let standard = CadStandard::new("Client")
+            .with_title_block("TB", &["TITLE"])
+            .with_units(crate::io::units::Unit::Inches);
+        let report = StandardsChecker::new(standard.clone())
+            .unwrap()
+            .check(&Document::new());
+
+        let result = report.to_check_result("drawings/plan.cdy");
+        assert_eq!(result.status, CheckStatus::Failure);
+        assert_eq!(result.status.to_exit_code(), 2);
+        assert_eq!(result.annotations.len(), 2);
+        assert_eq!(result.annotations[0].path, "drawings/plan.cdy");
+        assert_eq!(
+            result.annotations[0].title.as_deref(),
+            Some("standards/units")
+        );
+        assert_eq!(result.metadata["standard"], "Client");
+
+        let lenient = standard
+            .with_warning(RuleKind::Units)
+            .with_warning(RuleKind::TitleBlock);
+        let report = StandardsChecker::new(lenient)
+            .unwrap()
+            .check(&Document::new());
+        let result = report.to_check_result("plan.cdy");
+        assert_eq!(result.status, CheckStatus::Warning);
+        assert!(result
+            .annotations
+            .iter()
+            .all(|a| a.level == AnnotationLevel::Warning));
+    }
+}
@@ -0,0 +1,397 @@
+//! Organization standards library
+//!
+//! An organization publishes its approved drawing templates, block libraries,
+//! linetype definitions, dimension styles and [`CadStandard`](super::CadStandard)
+//! files to one central location. The [`LibraryManifest`] stored there lists
+//! every published version of every asset. Each client keeps a
+//! [`LibraryLock`] recording what it has installed and which assets it has
+//! pinned to a particular version; [`LibraryLock::plan`] compares the two and
+//! says what should be downloaded and which newer versions are being held
+//! back by a pin.
+//!
+//! Fetching and publishing go through
+//! [`LibrarySync`](super::sync::LibrarySync) and
+//! [`LibraryPublisher`](super::sync::LibraryPublisher), which need the
+//! `native` feature.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use super::{StandardsError, StandardsResult};
+
+/// Kind of library asset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetKind {
+    /// Drawing template
+    Template,
+    /// Block library drawing
+    BlockLibrary,
+    /// Linetype definitions
+    LineTypes,
+    /// Dimension styles
+    DimensionStyles,
+    /// CAD standard rulebook
+    Standard,
+}
+
+impl AssetKind {
+    /// Folder the kind is stored under, remotely and in the local cache
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            AssetKind::Template => "templates",
+            AssetKind::BlockLibrary => "blocks",
+            AssetKind::LineTypes => "linetypes",
+            AssetKind::DimensionStyles => "dimstyles",
+            AssetKind::Standard => "standards",
+        }
+    }
+}
+
+/// One published version of an asset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryAsset {
+    /// Asset name, unique per library (`A1-LANDSCAPE`, `ISO-DIMS`)
+    pub name: String,
+    /// Asset kind
+    pub kind: AssetKind,
+    /// Version, starting at 1 and increasing with each publish
+    pub version: u32,
+    /// Object path relative to the library root
+    pub path: String,
+    /// Hex SHA-256 of the content
+    pub sha256: String,
+    /// Content size in bytes
+    pub size: u64,
+    /// When this version was published
+    pub published_at: DateTime<Utc>,
+    /// Release notes shown with update notifications
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+/// Index of everything the organization has published
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryManifest {
+    /// Publishing organization
+    pub organization: String,
+    /// Every published version, oldest first
+    #[serde(default)]
+    pub assets: Vec<LibraryAsset>,
+}
+
+impl LibraryManifest {
+    /// Create an empty manifest
+    pub fn new(organization: &str) -> Self {
+        Self {
+            organization: organization.to_string(),
+            assets: Vec::new(),
+        }
+    }
+
+    /// Load a manifest from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> StandardsResult<Self> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Save the manifest as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> StandardsResult<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Add a newly published version
+    ///
+    /// Versions of an asset must increase and keep the asset's kind.
+    pub fn publish(&mut self, asset: LibraryAsset) -> StandardsResult<()> {
+        if let Some(latest) = self.latest(&asset.name) {
+            if asset.version <= latest.version || asset.kind != latest.kind {
+                return Err(StandardsError::VersionConflict {
+                    name: asset.name,
+                    version: asset.version,
+                });
+            }
+        }
+        self.assets.push(asset);
+        Ok(())
+    }
+
+    /// Version number the next publish of `name` should use
+    pub fn next_version(&self, name: &str) -> u32 {
+        self.latest(name).map_or(1, |asset| asset.version + 1)
+    }
+
+    /// Newest version of an asset
+    pub fn latest(&self, name: &str) -> Option<&LibraryAsset> {
+        self.assets
+            .iter()
+            .filter(|asset| asset.name == name)
+            .max_by_key(|asset| asset.version)
+    }
+
+    /// A specific version of an asset
+    pub fn version(&self, name: &str, version: u32) -> Option<&LibraryAsset> {
+        self.assets
+            .iter()
+            .find(|asset| asset.name == name && asset.version == version)
+    }
+
+    /// Asset names, sorted
+    pub fn names(&self) -> BTreeSet<&str> {
+        self.assets
+            .iter()
+            .map(|asset| asset.name.as_str())
+            .collect()
+    }
+}
+
+/// Which version of an asset a client wants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionPin {
+    /// Follow the newest published version
+    #[default]
+    Latest,
+    /// Stay on one version until the pin is changed
+    Exact(u32),
+}
+
+/// An asset present in the local cache
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstalledAsset {
+    /// Asset kind
+    pub kind: AssetKind,
+    /// Installed version
+    pub version: u32,
+    /// Hex SHA-256 of the installed content
+    pub sha256: String,
+    /// File path relative to the cache directory
+    pub path: String,
+    /// When it was installed
+    pub installed_at: DateTime<Utc>,
+}
+
+/// What [`LibraryLock::plan`] proposes for one asset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateAction {
+    /// Not installed yet
+    Install,
+    /// A newer version is wanted
+    Upgrade,
+    /// A pin asks for an older version than the installed one
+    Downgrade,
+    /// Up to date with the pin, but a newer version has been published
+    Held,
+}
+
+/// A pending change or a held-back update
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryUpdate {
+    /// Asset name
+    pub name: String,
+    /// Asset kind
+    pub kind: AssetKind,
+    /// Installed version, if any
+    pub installed: Option<u32>,
+    /// Version the pin resolves to
+    pub target: u32,
+    /// Newest published version
+    pub latest: u32,
+    /// Proposed action
+    pub action: UpdateAction,
+    /// Release notes of the newest version
+    pub notes: Option<String>,
+}
+
+impl LibraryUpdate {
+    /// Whether applying the update needs a download
+    pub fn needs_download(&self) -> bool {
+        self.action != UpdateAction::Held
+    }
+}
+
+/// Client-side record of pins and installed versions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LibraryLock {
+    /// Pins by asset name; unlisted assets follow [`VersionPin::Latest`]
+    #[serde(default)]
+    pub pins: BTreeMap<String, VersionPin>,
+    /// Installed assets by name
+    #[serde(default)]
+    pub installed: BTreeMap<String, InstalledAsset>,
+}
+
+impl LibraryLock {
+    /// Load a lock file, or start empty if there is none yet
+    pub fn load_or_default(path: impl AsRef<Path>) -> StandardsResult<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Save the lock file as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> StandardsResult<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Pin an asset
+    pub fn pin(&mut self, name: &str, pin: VersionPin) {
+        match pin {
+            VersionPin::Latest => self.pins.remove(name),
+            VersionPin::Exact(_) => self.pins.insert(name.to_string(), pin),
+        };
+    }
+
+    /// Pin in effect for an asset
+    pub fn pin_of(&self, name: &str) -> VersionPin {
+        self.pins.get(name).copied().unwrap_or_default()
+    }
+
+    /// Record a completed install
+    pub fn record(&mut self, asset: &LibraryAsset, path: &str) {
+        self.installed.insert(
+            asset.name.clone(),
+            InstalledAsset {
+                kind: asset.kind,
+                version: asset.version,
+                sha256: asset.sha256.clone(),
+                path: path.to_string(),
+                installed_at: Utc::now(),
+            },
+        );
+    }
+
+    /// Compare against a manifest
+    ///
+    /// Assets already on their target version with nothing newer published
+    /// are left out. A pin naming a version the manifest does not have is an
+    /// error, since silently following latest would defeat the pin.
+    pub fn plan(&self, manifest: &LibraryManifest) -> StandardsResult<Vec<LibraryUpdate>> {
+        let mut updates = Vec::new();
+
+        for name in manifest.names() {
+            let latest = manifest.latest(name).expect("name comes from manifest");
+            let target = match self.pin_of(name) {
+                VersionPin::Latest => latest,
+                VersionPin::Exact(version) => {
+                    manifest
+                        .version(name, version)
+                        .ok_or_else(|| StandardsError::NotPublished {
+                            name: name.to_string(),
+                            version,
+                        })?
+                }
+            };
+            let installed = self.installed.get(name).map(|asset| asset.version);
+
+            let action = match installed {
+                None => UpdateAction::Install,
+                Some(v) if v < target.version => UpdateAction::Upgrade,
+                Some(v) if v > target.version => UpdateAction::Downgrade,
+                Some(_) if latest.version > target.version => UpdateAction::Held,
+                Some(_) => continue,
+            };
+
+            updates.push(LibraryUpdate {
+                name: name.to_string(),
+                kind: target.kind,
+                installed,
+                target: target.version,
+                latest: latest.version,
+                action,
+                notes: latest.notes.clone(),
+            });
+        }
+
+        Ok(updates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(name: &str, version: u32) -> LibraryAsset {
+        LibraryAsset {
+            name: name.to_string(),
+            kind: AssetKind::Template,
+            version,
+            path: format!("templates/{}/v{}/{}.dwt", name, version, name),
+            sha256: format!("{:064}", version),
+            size: 0,
+            published_at: Utc::now(),
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn test_manifest_versions() {
+        let mut manifest = LibraryManifest::new("Acme");
+        assert_eq!(manifest.next_version("A1"), 1);
+        manifest.publish(asset("A1", 1)).unwrap();
+        manifest.publish(asset("A1", 2)).unwrap();
+
+        assert_eq!(manifest.next_version("A1"), 3);
+        assert_eq!(manifest.latest("A1").unwrap().version, 2);
+        assert!(matches!(
+            manifest.publish(asset("A1", 2)),
+            Err(StandardsError::VersionConflict { version: 2, .. })
+        ));
+
+        let mut blocks = asset("A1", 3);
+        blocks.kind = AssetKind::BlockLibrary;
+        assert!(manifest.publish(blocks).is_err());
+    }
+
+    #[test]
+    fn test_plan_respects_pins() {
+        let mut manifest = LibraryManifest::new("Acme");
+        for version in 1..=3 {
+            manifest.publish(asset("A1", version)).unwrap();
+        }
+        manifest.publish(asset("ISO", 1)).unwrap();
+
+        let mut lock = LibraryLock::default();
+        lock.record(&asset("A1", 1), "templates/A1-v1.dwt");
+        lock.record(&asset("ISO", 1), "templates/ISO-v1.dwt");
+
+        let plan = lock.plan(&manifest).unwrap();
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].action, UpdateAction::Upgrade);
+        assert_eq!(plan[0].target, 3);
+
+        lock.pin("A1", VersionPin::Exact(2));
+        let plan = lock.plan(&manifest).unwrap();
+        assert_eq!(plan[0].action, UpdateAction::Upgrade);
+        assert_eq!(plan[0].target, 2);
+
+        lock.record(&asset("A1", 2), "templates/A1-v2.dwt");
+        let plan = lock.plan(&manifest).unwrap();
+        assert_eq!(plan[0].action, UpdateAction::Held);
+        assert_eq!(plan[0].latest, 3);
+        assert!(!plan[0].needs_download());
+
+        lock.pin("A1", VersionPin::Exact(1));
+        assert_eq!(
+            lock.plan(&manifest).unwrap()[0].action,
+            UpdateAction::Downgrade
+        );
+
+        lock.pin("A1", VersionPin::Exact(9));
+        assert!(matches!(
+            lock.plan(&manifest),
+            Err(StandardsError::NotPublished { version: 9, .. })
+        ));
+
+        lock.pin("A1", VersionPin::Latest);
+        assert!(lock.pins.is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+//! # CADDY Standards Auditing
+//!
+//! Checks deliverable drawings against a client CAD standard before issue:
+//!
+//! - **Layers**: names matched against wildcard patterns (`A-WALL-*`), with
+//!   expected color, linetype, lineweight, and plot setting per pattern, and
+//!   layers the deliverable must contain
+//! - **Linetypes**: only approved linetypes on layers and entity overrides
+//! - **Text**: approved heights (within a tolerance) and text styles
+//! - **Title block**: the title block is inserted and its required fields
+//!   are filled in
+//! - **Units**: the drawing uses the contracted units
+//!
+//! Standards are plain JSON, so they can live in the project repository next
+//! to the drawings. With the `native` feature a report converts into a
+//! [`CheckResult`](crate::integrations::CheckResult) for the CI integrations.
+//!
+//! The organization's approved templates, block libraries, linetypes,
+//! dimension styles and standards are distributed as a versioned
+//! [library](library): clients pin versions in a lock file and, with the
+//! `native` feature, pull updates from cloud storage through
+//! [`sync::LibrarySync`].
+//!
+//! ## Example
+//!
+//! ```
+//! use caddy::io::document::{Color, Document, Layer, LineType, LineWeight};
+//! use caddy::standards::{CadStandard, LayerRule, StandardsChecker};
+//!
+//! fn layer(name: &str) -> Layer {
+//!     Layer {
+//!         name: name.to_string(),
+//!         color: Color::white(),
+//!         line_type: LineType::Continuous,
+//!         line_weight: LineWeight::Default,
+//!         visible: true,
+//!         locked: false,
+//!         frozen: false,
+//!         plottable: true,
+//!         confidential: false,
+//!     }
+//! }
+//!
+//! let standard = CadStandard::new("Client BIM Standard")
+//!     .with_layer(LayerRule::new("A-*"))
+//!     .with_layer(LayerRule::new("S-GRID").required());
+//!
+//! let mut doc = Document::new();
+//! doc.add_layer(layer("A-WALL"));
+//! doc.add_layer(layer("walls"));
+//!
+//! let report = StandardsChecker::new(standard).unwrap().check(&doc);
+//! assert!(!report.passed());
+//! assert_eq!(report.errors().count(), 2);
+//! ```
+
+pub mod audit;
+#[cfg(feature = "native")]
+pub mod ci;
+pub mod library;
+pub mod standard;
+#[cfg(feature = "native")]
+pub mod sync;
+
+use thiserror::Error;
+
+pub use audit::{Finding, RuleKind, Severity, StandardsChecker, StandardsReport};
+pub use library::{
+    AssetKind, LibraryAsset, LibraryLock, LibraryManifest, LibraryUpdate, UpdateAction,
+    VersionPin,
+};
+pub use standard::{CadStandard, LayerRule, TextRule, TitleBlockRule};
+#[cfg(feature = "native")]
+pub use sync::{LibraryPublisher, LibrarySync, SyncReport};
+
+/// Standards errors
+#[derive(Debug, Error)]
+pub enum StandardsError {
+    /// A rule in the standard cannot be applied
+    #[error("Invalid rule: {0}")]
+    InvalidRule(String),
+
+    /// File system error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Standard file is malformed
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    /// A library asset version is not newer than the published one, or
+    /// changes the asset's kind
+    #[error("Cannot publish {name} v{version}")]
+    VersionConflict {
+        /// Asset name
+        name: String,
+        /// Asset version
+        version: u32,
+    },
+
+    /// A pinned library version is not in the manifest
+    #[error("{name} v{version} is not published")]
+    NotPublished {
+        /// Asset name
+        name: String,
+        /// Asset version
+        version: u32,
+    },
+
+    /// Downloaded library content does not match the manifest
+    #[error("Checksum mismatch for {name} v{version}")]
+    ChecksumMismatch {
+        /// Asset name
+        name: String,
+        /// Asset version
+        version: u32,
+    },
+
+    /// Library storage error
+    #[cfg(feature = "native")]
+    #[error("Storage error: {0}")]
+    Storage(#[from] crate::enterprise::cloud::StorageError),
+}
+
+/// Result type for standards operations
+pub type StandardsResult<T> = Result<T, StandardsError>;
+
+/// Case-insensitive wildcard match: `*` matches any run, `?` one character
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_uppercase().chars().collect();
+    let name: Vec<char> = name.to_uppercase().chars().collect();
+
+    // Greedy match, backtracking to the most recent `*`
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((sp, sn)) = star {
+            p = sp + 1;
+            n = sn + 1;
+            star = Some((sp, sn + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("A-WALL-*", "a-wall-full"));
+        assert!(matches_pattern("A-WALL-*", "A-WALL-"));
+        assert!(!matches_pattern("A-WALL-*", "A-WALL"));
+        assert!(matches_pattern("?-GRID", "S-GRID"));
+        assert!(!matches_pattern("?-GRID", "SS-GRID"));
+        assert!(matches_pattern("*-ANNO-*-TEXT", "A-ANNO-DIMS-NOTE-TEXT"));
+        assert!(matches_pattern("*", ""));
+        assert!(!matches_pattern("", "0"));
+    }
+}
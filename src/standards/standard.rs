@@ -0,0 +1,345 @@
+//! Standard definitions
+//!
+//! A [`CadStandard`] is the client's rulebook. Every section is optional: an
+//! empty list or `None` means the standard says nothing about it.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use super::audit::RuleKind;
+use super::{matches_pattern, StandardsError, StandardsResult};
+use crate::io::document::{Color, LineWeight};
+use crate::io::units::Unit;
+
+/// Expected properties for layers matching a name pattern
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerRule {
+    /// Wildcard pattern (`*`, `?`), case-insensitive
+    pub pattern: String,
+    /// Required color
+    #[serde(default)]
+    pub color: Option<Color>,
+    /// Required linetype name
+    #[serde(default)]
+    pub line_type: Option<String>,
+    /// Required lineweight
+    #[serde(default)]
+    pub line_weight: Option<LineWeight>,
+    /// Required plot setting
+    #[serde(default)]
+    pub plottable: Option<bool>,
+    /// At least one layer must match
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl LayerRule {
+    /// Allow layers matching a pattern
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            color: None,
+            line_type: None,
+            line_weight: None,
+            plottable: None,
+            required: false,
+        }
+    }
+
+    /// Require a color
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Require a linetype
+    pub fn with_line_type(mut self, name: &str) -> Self {
+        self.line_type = Some(name.to_string());
+        self
+    }
+
+    /// Require a lineweight
+    pub fn with_line_weight(mut self, weight: LineWeight) -> Self {
+        self.line_weight = Some(weight);
+        self
+    }
+
+    /// Require layers to plot (or not)
+    pub fn with_plottable(mut self, plottable: bool) -> Self {
+        self.plottable = Some(plottable);
+        self
+    }
+
+    /// Require at least one matching layer
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Whether a layer name matches this rule
+    pub fn matches(&self, layer: &str) -> bool {
+        matches_pattern(&self.pattern, layer)
+    }
+}
+
+/// Text rules
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextRule {
+    /// Approved heights in drawing units (empty allows any)
+    #[serde(default)]
+    pub heights: Vec<f64>,
+    /// Allowed deviation from an approved height
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f64,
+    /// Approved text style names (empty allows any)
+    #[serde(default)]
+    pub styles: Vec<String>,
+}
+
+fn default_tolerance() -> f64 {
+    1e-3
+}
+
+impl Default for TextRule {
+    fn default() -> Self {
+        Self {
+            heights: Vec::new(),
+            tolerance: default_tolerance(),
+            styles: Vec::new(),
+        }
+    }
+}
+
+impl TextRule {
+    /// Whether a height is approved
+    pub fn allows_height(&self, height: f64) -> bool {
+        self.heights.is_empty()
+            || self
+                .heights
+                .iter()
+                .any(|h| (h - height).abs() <= self.tolerance)
+    }
+
+    /// Whether a style is approved
+    pub fn allows_style(&self, style: &str) -> bool {
+        self.styles.is_empty() || self.styles.iter().any(|s| s.eq_ignore_ascii_case(style))
+    }
+}
+
+/// Title block rules
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TitleBlockRule {
+    /// Title block name
+    pub block: String,
+    /// Attribute tags that must be filled in
+    #[serde(default)]
+    pub fields: Vec<String>,
+}
+
+/// A client CAD standard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CadStandard {
+    /// Standard name, shown on reports
+    pub name: String,
+    /// Contracted drawing units
+    #[serde(default)]
+    pub units: Option<Unit>,
+    /// Layer rules; a layer takes the first rule it matches
+    #[serde(default)]
+    pub layers: Vec<LayerRule>,
+    /// Accept layers no rule matches
+    #[serde(default)]
+    pub allow_other_layers: bool,
+    /// Approved linetype names (empty allows any)
+    #[serde(default)]
+    pub line_types: Vec<String>,
+    /// Text rules
+    #[serde(default)]
+    pub text: Option<TextRule>,
+    /// Title block rules
+    #[serde(default)]
+    pub title_block: Option<TitleBlockRule>,
+    /// Rules whose findings are warnings rather than failures
+    #[serde(default)]
+    pub warnings: Vec<RuleKind>,
+}
+
+impl CadStandard {
+    /// Create an empty standard
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            units: None,
+            layers: Vec::new(),
+            allow_other_layers: false,
+            line_types: Vec::new(),
+            text: None,
+            title_block: None,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Load a standard from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> StandardsResult<Self> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Save the standard as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> StandardsResult<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Require units
+    pub fn with_units(mut self, units: Unit) -> Self {
+        self.units = Some(units);
+        self
+    }
+
+    /// Add a layer rule
+    pub fn with_layer(mut self, rule: LayerRule) -> Self {
+        self.layers.push(rule);
+        self
+    }
+
+    /// Approve a linetype
+    pub fn with_line_type(mut self, name: &str) -> Self {
+        self.line_types.push(name.to_string());
+        self
+    }
+
+    /// Set text rules
+    pub fn with_text(mut self, rule: TextRule) -> Self {
+        self.text = Some(rule);
+        self
+    }
+
+    /// Set title block rules
+    pub fn with_title_block(mut self, block: &str, fields: &[&str]) -> Self {
+        self.title_block = Some(TitleBlockRule {
+            block: block.to_string(),
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+        });
+        self
+    }
+
+    /// Report a rule's findings as warnings
+    pub fn with_warning(mut self, rule: RuleKind) -> Self {
+        self.warnings.push(rule);
+        self
+    }
+
+    /// The first layer rule a layer name matches
+    pub fn layer_rule(&self, layer: &str) -> Option<&LayerRule> {
+        self.layers.iter().find(|r| r.matches(layer))
+    }
+
+    /// Check the standard itself is usable
+    pub fn validate(&self) -> StandardsResult<()> {
+        if let Some(i) = self.layers.iter().position(|r| r.pattern.is_empty()) {
+            return Err(StandardsError::InvalidRule(format!(
+                "layer rule {} has an empty pattern",
+                i + 1
+            )));
+        }
+        if let Some(text) = &self.text {
+            if text.tolerance.is_nan() || text.tolerance < 0.0 {
+                return Err(StandardsError::InvalidRule(format!(
+                    "text height tolerance {} must be zero or more",
+                    text.tolerance
+                )));
+            }
+            if let Some(h) = text.heights.iter().find(|h| !h.is_finite() || **h <= 0.0) {
+                return Err(StandardsError::InvalidRule(format!(
+                    "text height {} must be positive",
+                    h
+                )));
+            }
+        }
+        if self
+            .title_block
+            .as_ref()
+            .is_some_and(|t| t.block.is_empty())
+        {
+            return Err(StandardsError::InvalidRule(
+                "title block name is empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_roundtrip_and_defaults() {
+        let standard = CadStandard::new("Client")
+            .with_units(Unit::Millimeters)
+            .with_layer(
+                LayerRule::new("A-*")
+                    .with_color(Color::new(255, 0, 0))
+                    .required(),
+            )
+            .with_line_type("Continuous")
+            .with_text(TextRule {
+                heights: vec![2.5, 3.5],
+                ..TextRule::default()
+            })
+            .with_title_block("TITLE_A1", &["SHEET_NUMBER", "TITLE"])
+            .with_warning(RuleKind::TextStyle);
+
+        let path = std::env::temp_dir().join(format!("standard-{}.json", uuid::Uuid::new_v4()));
+        standard.save(&path).unwrap();
+        assert_eq!(CadStandard::load(&path).unwrap(), standard);
+        std::fs::remove_file(&path).ok();
+
+        let minimal: CadStandard =
+            serde_json::from_str(r#"{"name": "Min", "text": {"heights": [2.5]}}"#).unwrap();
+        assert!(minimal.layers.is_empty());
+        assert_eq!(minimal.text.unwrap().tolerance, 1e-3);
+    }
+
+    #[test]
+    fn test_rules() {
+        let standard = CadStandard::new("Client")
+            .with_layer(LayerRule::new("A-WALL*").with_line_type("Continuous"))
+            .with_layer(LayerRule::new("A-*"));
+        assert_eq!(
+            standard.layer_rule("a-wall-ext").unwrap().pattern,
+            "A-WALL*"
+        );
+        assert_eq!(standard.layer_rule("A-DOOR").unwrap().pattern, "A-*");
+        assert!(standard.layer_rule("S-GRID").is_none());
+
+        let text = TextRule {
+            heights: vec![2.5],
+            tolerance: 0.01,
+            styles: vec!["ISO".to_string()],
+        };
+        assert!(text.allows_height(2.505));
+        assert!(!text.allows_height(2.6));
+        assert!(text.allows_style("iso"));
+        assert!(!text.allows_style("Standard"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(CadStandard::new("ok").validate().is_ok());
+        assert!(CadStandard::new("bad")
+            .with_layer(LayerRule::new(""))
+            .validate()
+            .is_err());
+        let bad_height = CadStandard::new("bad").with_text(TextRule {
+            heights: vec![0.0],
+            ..TextRule::default()
+        });
+        assert!(matches!(
+            bad_height.validate(),
+            Err(StandardsError::InvalidRule(_))
+        ));
+    }
+}
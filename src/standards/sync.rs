@@ -0,0 +1,458 @@
+//! Library distribution
+//!
+//! [`LibraryPublisher`] uploads assets and keeps the manifest at the library
+//! root up to date. [`LibrarySync`] runs on each client: it reads the
+//! manifest, downloads whatever the local [`LibraryLock`] plan calls for into
+//! a cache directory, verifies every download against the manifest checksum,
+//! and broadcasts a [`LibraryUpdate`] for each change or held-back version so
+//! the UI can tell the user their standards moved.
+//!
+//! Any [`CloudStorage`] backend works as the central location, including an
+//! S3-compatible server on the office network.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use caddy::enterprise::cloud::S3Storage;
+//! use caddy::standards::library::{AssetKind, VersionPin};
+//! use caddy::standards::sync::{LibraryPublisher, LibrarySync};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let storage = Arc::new(S3Storage::new("acme-cad", "us-east-1").await?);
+//!
+//! let publisher = LibraryPublisher::new(storage.clone(), "library", "Acme");
+//! let template = std::fs::read("A1-LANDSCAPE.dwt")?;
+//! publisher
+//!     .publish("A1-LANDSCAPE", AssetKind::Template, "A1-LANDSCAPE.dwt", &template, None)
+//!     .await?;
+//!
+//! let sync = LibrarySync::new(storage, "library", "/var/cache/caddy/library")?;
+//! let mut updates = sync.subscribe();
+//! sync.pin("ISO-DIMS", VersionPin::Exact(4)).await?;
+//! let report = sync.sync().await?;
+//! println!("{} assets updated", report.applied.len());
+//! # let _ = updates.try_recv();
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+
+use super::library::{LibraryAsset, LibraryLock, LibraryManifest, LibraryUpdate, VersionPin};
+use super::{AssetKind, StandardsError, StandardsResult};
+use crate::enterprise::cloud::{CloudStorage, StorageError};
+
+/// Manifest object name at the library root
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Lock file name in the client cache directory
+pub const LOCK_FILE: &str = "library.lock.json";
+
+fn object_path(prefix: &str, path: &str) -> String {
+    if prefix.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}/{}", prefix.trim_end_matches('/'), path)
+    }
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+async fn fetch_manifest(
+    storage: &dyn CloudStorage,
+    prefix: &str,
+) -> StandardsResult<Option<LibraryManifest>> {
+    match storage
+        .download_file(&object_path(prefix, MANIFEST_FILE))
+        .await
+    {
+        Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        Err(StorageError::FileNotFound(_)) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Publishes assets to the central library
+pub struct LibraryPublisher {
+    storage: Arc<dyn CloudStorage>,
+    prefix: String,
+    organization: String,
+}
+
+impl LibraryPublisher {
+    /// Publisher for the library rooted at `prefix`
+    pub fn new(storage: Arc<dyn CloudStorage>, prefix: &str, organization: &str) -> Self {
+        Self {
+            storage,
+            prefix: prefix.to_string(),
+            organization: organization.to_string(),
+        }
+    }
+
+    /// Upload a new version of an asset and add it to the manifest
+    ///
+    /// The content is written before the manifest, so clients never see a
+    /// manifest entry whose file is missing.
+    pub async fn publish(
+        &self,
+        name: &str,
+        kind: AssetKind,
+        file_name: &str,
+        data: &[u8],
+        notes: Option<String>,
+    ) -> StandardsResult<LibraryAsset> {
+        let mut manifest = fetch_manifest(self.storage.as_ref(), &self.prefix)
+            .await?
+            .unwrap_or_else(|| LibraryManifest::new(&self.organization));
+
+        let version = manifest.next_version(name);
+        let asset = LibraryAsset {
+            name: name.to_string(),
+            kind,
+            version,
+            path: format!("{}/{}/v{}/{}", kind.dir_name(), name, version, file_name),
+            sha256: sha256_hex(data),
+            size: data.len() as u64,
+            published_at: Utc::now(),
+            notes,
+        };
+        manifest.publish(asset.clone())?;
+
+        self.storage
+            .upload_file(&object_path(&self.prefix, &asset.path), data)
+            .await?;
+        self.storage
+            .upload_file(
+                &object_path(&self.prefix, MANIFEST_FILE),
+                &serde_json::to_vec_pretty(&manifest)?,
+            )
+            .await?;
+
+        log::info!("Published {} v{} to the standards library", name, version);
+        Ok(asset)
+    }
+}
+
+/// Outcome of [`LibrarySync::sync`]
+#[derive(Debug, Clone, Default)]
+pub struct SyncReport {
+    /// Installs, upgrades and downgrades that were applied
+    pub applied: Vec<LibraryUpdate>,
+    /// Newer versions not installed because of a pin
+    pub held: Vec<LibraryUpdate>,
+}
+
+/// Keeps a client's local copy of the library in step with the central one
+pub struct LibrarySync {
+    storage: Arc<dyn CloudStorage>,
+    prefix: String,
+    cache_dir: PathBuf,
+    lock: RwLock<LibraryLock>,
+    updates: broadcast::Sender<LibraryUpdate>,
+}
+
+impl LibrarySync {
+    /// Client for the library rooted at `prefix`, caching under `cache_dir`
+    pub fn new(
+        storage: Arc<dyn CloudStorage>,
+        prefix: &str,
+        cache_dir: impl Into<PathBuf>,
+    ) -> StandardsResult<Self> {
+        let cache_dir = cache_dir.into();
+        let lock = LibraryLock::load_or_default(cache_dir.join(LOCK_FILE))?;
+        let (updates, _) = broadcast::channel(64);
+
+        Ok(Self {
+            storage,
+            prefix: prefix.to_string(),
+            cache_dir,
+            lock: RwLock::new(lock),
+            updates,
+        })
+    }
+
+    /// Receive update notifications
+    pub fn subscribe(&self) -> broadcast::Receiver<LibraryUpdate> {
+        self.updates.subscribe()
+    }
+
+    /// Current lock state
+    pub async fn lock(&self) -> LibraryLock {
+        self.lock.read().await.clone()
+    }
+
+    /// Local path of an installed asset
+    pub async fn installed_path(&self, name: &str) -> Option<PathBuf> {
+        self.lock
+            .read()
+            .await
+            .installed
+            .get(name)
+            .map(|asset| self.cache_dir.join(&asset.path))
+    }
+
+    /// Pin an asset and save the lock file; takes effect on the next sync
+    pub async fn pin(&self, name: &str, pin: VersionPin) -> StandardsResult<()> {
+        let mut lock = self.lock.write().await;
+        lock.pin(name, pin);
+        self.save_lock(&lock)
+    }
+
+    /// Download the central manifest
+    pub async fn fetch_manifest(&self) -> StandardsResult<LibraryManifest> {
+        fetch_manifest(self.storage.as_ref(), &self.prefix)
+            .await?
+            .ok_or_else(|| {
+                StorageError::FileNotFound(object_path(&self.prefix, MANIFEST_FILE)).into()
+            })
+    }
+
+    /// Compare the local copy with the central manifest and notify
+    /// subscribers, without downloading anything
+    pub async fn check(&self) -> StandardsResult<Vec<LibraryUpdate>> {
+        let manifest = self.fetch_manifest().await?;
+        let updates = self.lock.read().await.plan(&manifest)?;
+        for update in &updates {
+            self.notify(update);
+        }
+        Ok(updates)
+    }
+
+    /// Bring the local copy in line with the manifest and the pins
+    ///
+    /// A download whose checksum does not match the manifest fails the sync
+    /// and leaves the previously installed version in place.
+    pub async fn sync(&self) -> StandardsResult<SyncReport> {
+        let manifest = self.fetch_manifest().await?;
+        let mut lock = self.lock.write().await;
+        let mut report = SyncReport::default();
+
+        for update in lock.plan(&manifest)? {
+            if !update.needs_download() {
+                self.notify(&update);
+                report.held.push(update);
+                continue;
+            }
+
+            let asset = manifest
+                .version(&update.name, update.target)
+                .expect("plan targets a published version");
+            let relative = self.install(asset).await?;
+            lock.record(asset, &relative);
+            self.save_lock(&lock)?;
+
+            log::info!(
+                "Installed {} v{} from the standards library",
+                asset.name,
+                asset.version
+            );
+            self.notify(&update);
+            report.applied.push(update);
+        }
+
+        Ok(report)
+    }
+
+    async fn install(&self, asset: &LibraryAsset) -> StandardsResult<String> {
+        let data = self
+            .storage
+            .download_file(&object_path(&self.prefix, &asset.path))
+            .await?;
+        if data.len() as u64 != asset.size || sha256_hex(&data) != asset.sha256 {
+            return Err(StandardsError::ChecksumMismatch {
+                name: asset.name.clone(),
+                version: asset.version,
+            });
+        }
+
+        let file_name = Path::new(&asset.path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&asset.name);
+        let relative = format!(
+            "{}/{}/v{}/{}",
+            asset.kind.dir_name(),
+            asset.name,
+            asset.version,
+            file_name
+        );
+        let path = self.cache_dir.join(&relative);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, &data)?;
+
+        Ok(relative)
+    }
+
+    fn save_lock(&self, lock: &LibraryLock) -> StandardsResult<()> {
+        std::fs::create_dir_all(&self.cache_dir)?;
+        lock.save(self.cache_dir.join(LOCK_FILE))
+    }
+
+    fn notify(&self, update: &LibraryUpdate) {
+        // No subscribers is fine
+        let _ = self.updates.send(update.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enterprise::cloud::storage::{FileMetadata, StorageStats};
+    use crate::standards::library::UpdateAction;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::time::SystemTime;
+
+    #[derive(Default)]
+    struct MemoryStorage {
+        files: std::sync::Mutex<HashMap<String, Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl CloudStorage for MemoryStorage {
+        async fn upload_file(&self, path: &str, data: &[u8]) -> Result<FileMetadata, StorageError> {
+            self.files
+                .lock()
+                .unwrap()
+                .insert(path.to_string(), data.to_vec());
+            self.get_metadata(path).await
+        }
+
+        async fn download_file(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+            self.files
+                .lock()
+                .unwrap()
+                .get(path)
+                .cloned()
+                .ok_or_else(|| StorageError::FileNotFound(path.to_string()))
+        }
+
+        async fn delete_file(&self, path: &str) -> Result<(), StorageError> {
+            self.files.lock().unwrap().remove(path);
+            Ok(())
+        }
+
+        async fn list_files(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+            Ok(self
+                .files
+                .lock()
+                .unwrap()
+                .keys()
+                .filter(|k| k.starts_with(prefix))
+                .cloned()
+                .collect())
+        }
+
+        async fn get_metadata(&self, path: &str) -> Result<FileMetadata, StorageError> {
+            let size = self.download_file(path).await?.len() as u64;
+            Ok(FileMetadata {
+                path: path.to_string(),
+                size,
+                modified: SystemTime::now(),
+                hash: String::new(),
+                version: 1,
+                content_type: None,
+                custom_metadata: HashMap::new(),
+            })
+        }
+
+        async fn file_exists(&self, path: &str) -> Result<bool, StorageError> {
+            Ok(self.files.lock().unwrap().contains_key(path))
+        }
+
+        async fn copy_file(&self, source: &str, destination: &str) -> Result<(), StorageError> {
+            let data = self.download_file(source).await?;
+            self.upload_file(destination, &data).await.map(|_| ())
+        }
+
+        async fn move_file(&self, source: &str, destination: &str) -> Result<(), StorageError> {
+            self.copy_file(source, destination).await?;
+            self.delete_file(source).await
+        }
+
+        async fn get_stats(&self) -> Result<StorageStats, StorageError> {
+            Ok(StorageStats::default())
+        }
+
+        async fn create_presigned_url(
+            &self,
+            path: &str,
+            _expiry_secs: u64,
+        ) -> Result<String, StorageError> {
+            Ok(format!("memory://{}", path))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_and_sync() {
+        let storage: Arc<dyn CloudStorage> = Arc::new(MemoryStorage::default());
+        let cache = std::env::temp_dir().join(format!("caddy-library-{}", uuid::Uuid::new_v4()));
+        let publisher = LibraryPublisher::new(storage.clone(), "lib", "Acme");
+
+        publisher
+            .publish("A1", AssetKind::Template, "A1.dwt", b"v1", None)
+            .await
+            .unwrap();
+
+        let sync = LibrarySync::new(storage.clone(), "lib", &cache).unwrap();
+        let mut updates = sync.subscribe();
+        let report = sync.sync().await.unwrap();
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(updates.try_recv().unwrap().action, UpdateAction::Install);
+
+        let path = sync.installed_path("A1").await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"v1");
+
+        sync.pin("A1", VersionPin::Exact(1)).await.unwrap();
+        publisher
+            .publish(
+                "A1",
+                AssetKind::Template,
+                "A1.dwt",
+                b"v2",
+                Some("New border".to_string()),
+            )
+            .await
+            .unwrap();
+
+        let report = sync.sync().await.unwrap();
+        assert!(report.applied.is_empty());
+        let held = updates.try_recv().unwrap();
+        assert_eq!(held.action, UpdateAction::Held);
+        assert_eq!(held.latest, 2);
+        assert_eq!(held.notes.as_deref(), Some("New border"));
+
+        // A fresh client picks up the pin from the lock file
+        let reopened = LibrarySync::new(storage.clone(), "lib", &cache).unwrap();
+        assert_eq!(reopened.lock().await.pin_of("A1"), VersionPin::Exact(1));
+        reopened.pin("A1", VersionPin::Latest).await.unwrap();
+        assert_eq!(
+            reopened.sync().await.unwrap().applied[0].action,
+            UpdateAction::Upgrade
+        );
+        let path = reopened.installed_path("A1").await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"v2");
+
+        // Tampered content is rejected
+        storage
+            .upload_file("lib/templates/A1/v2/A1.dwt", b"xx")
+            .await
+            .unwrap();
+        let fresh = LibrarySync::new(storage, "lib", cache.join("other")).unwrap();
+        assert!(matches!(
+            fresh.sync().await,
+            Err(StandardsError::ChecksumMismatch { version: 2, .. })
+        ));
+
+        std::fs::remove_dir_all(&cache).ok();
+    }
+}
@@ -0,0 +1,226 @@
+//! Schedule export
+//!
+//! CSV is always available. XLSX workbooks (one sheet per schedule, with a
+//! bold header and totals row) need the `xlsx` feature.
+
+use super::schedule::ScheduleTable;
+#[cfg(feature = "xlsx")]
+use super::{TakeoffError, TakeoffResult};
+
+impl ScheduleTable {
+    /// Header row: key headers followed by column headers with units
+    pub fn header_row(&self) -> Vec<String> {
+        self.group_headers
+            .iter()
+            .cloned()
+            .chain(self.columns.iter().map(|c| c.display_header()))
+            .collect()
+    }
+
+    /// Render as CSV (RFC 4180) with a trailing totals row
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        push_record(&mut out, self.header_row());
+
+        for row in &self.rows {
+            let fields = row.keys.iter().cloned().chain(
+                self.columns
+                    .iter()
+                    .zip(&row.values)
+                    .map(|(c, v)| c.format(*v)),
+            );
+            push_record(&mut out, fields);
+        }
+
+        let labels = (0..self.group_headers.len()).map(|i| {
+            if i == 0 {
+                "Total".to_string()
+            } else {
+                String::new()
+            }
+        });
+        let totals = self
+            .columns
+            .iter()
+            .zip(&self.totals)
+            .map(|(c, v)| c.format(*v));
+        push_record(&mut out, labels.chain(totals));
+        out
+    }
+
+    /// Render as a single-sheet XLSX workbook
+    #[cfg(feature = "xlsx")]
+    pub fn to_xlsx(&self) -> TakeoffResult<Vec<u8>> {
+        write_xlsx(std::slice::from_ref(self))
+    }
+}
+
+fn push_record(out: &mut String, fields: impl IntoIterator<Item = String>) {
+    let fields: Vec<String> = fields.into_iter().map(|f| quote(&f)).collect();
+    out.push_str(&fields.join(","));
+    out.push_str("\r\n");
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Write several schedules into one workbook, one sheet each
+#[cfg(feature = "xlsx")]
+pub fn write_xlsx(tables: &[ScheduleTable]) -> TakeoffResult<Vec<u8>> {
+    use rust_xlsxwriter::{Format, Workbook};
+
+    let xlsx_err = |e: rust_xlsxwriter::XlsxError| TakeoffError::Export(e.to_string());
+    let bold = Format::new().set_bold();
+    let mut workbook = Workbook::new();
+    let mut used_names: Vec<String> = Vec::new();
+
+    for table in tables {
+        let sheet = workbook.add_worksheet();
+        sheet
+            .set_name(unique_sheet_name(&table.name, &mut used_names))
+            .map_err(xlsx_err)?;
+
+        for (col, header) in table.header_row().iter().enumerate() {
+            sheet
+                .write_string_with_format(0, col as u16, header, &bold)
+                .map_err(xlsx_err)?;
+            sheet
+                .set_column_width(col as u16, (header.chars().count() + 4).max(10) as f64)
+                .map_err(xlsx_err)?;
+        }
+
+        let key_cols = table.group_headers.len();
+        let formats: Vec<Format> = table
+            .columns
+            .iter()
+            .map(|c| Format::new().set_num_format(number_format(c.decimals)))
+            .collect();
+
+        for (r, row) in table.rows.iter().enumerate() {
+            let xr = r as u32 + 1;
+            for (c, key) in row.keys.iter().enumerate() {
+                sheet.write_string(xr, c as u16, key).map_err(xlsx_err)?;
+            }
+            for (c, value) in row.values.iter().enumerate() {
+                sheet
+                    .write_number_with_format(xr, (key_cols + c) as u16, *value, &formats[c])
+                    .map_err(xlsx_err)?;
+            }
+        }
+
+        let total_row = table.rows.len() as u32 + 1;
+        if key_cols > 0 {
+            sheet
+                .write_string_with_format(total_row, 0, "Total", &bold)
+                .map_err(xlsx_err)?;
+        }
+        for (c, value) in table.totals.iter().enumerate() {
+            let format = formats[c].clone().set_bold();
+            sheet
+                .write_number_with_format(total_row, (key_cols + c) as u16, *value, &format)
+                .map_err(xlsx_err)?;
+        }
+    }
+
+    workbook.save_to_buffer().map_err(xlsx_err)
+}
+
+#[cfg(feature = "xlsx")]
+fn number_format(decimals: usize) -> String {
+    if decimals == 0 {
+        "0".to_string()
+    } else {
+        format!("0.{}", "0".repeat(decimals))
+    }
+}
+
+/// Excel sheet names: at most 31 characters, none of `[]:*?/\`, unique
+#[cfg(feature = "xlsx")]
+fn unique_sheet_name(name: &str, used: &mut Vec<String>) -> String {
+    let clean: String = name
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .take(31)
+        .collect();
+    let base = if clean.trim().is_empty() {
+        "Schedule".to_string()
+    } else {
+        clean
+    };
+
+    let mut candidate = base.clone();
+    let mut n = 2;
+    while used.iter().any(|u| u.eq_ignore_ascii_case(&candidate)) {
+        let suffix = format!(" ({})", n);
+        let keep = 31 - suffix.len();
+        candidate = format!("{}{}", base.chars().take(keep).collect::<String>(), suffix);
+        n += 1;
+    }
+    used.push(candidate.clone());
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{Document, Entity, GeometryType, Line, Vec3};
+    use crate::takeoff::schedule::{Column, GroupBy, ScheduleDefinition};
+
+    fn table() -> ScheduleTable {
+        let mut doc = Document::new();
+        for (layer, length) in [("WALLS", 4.0), ("DOORS, EXT", 1.25), ("WALLS", 2.0)] {
+            doc.add_entity(Entity::new(
+                GeometryType::Line(Line {
+                    start: Vec3::zero(),
+                    end: Vec3::new(length, 0.0, 0.0),
+                }),
+                layer.to_string(),
+            ));
+        }
+        ScheduleDefinition::new("Lengths")
+            .group_by(GroupBy::Layer)
+            .column(Column::count("qty").with_header("Qty"))
+            .column(Column::length("len").with_header("Length").with_unit("m"))
+            .evaluate(&doc)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_csv() {
+        let csv = table().to_csv();
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(lines[0], "Layer,Qty,Length (m)");
+        assert_eq!(lines[1], "\"DOORS, EXT\",1,1.25");
+        assert_eq!(lines[2], "WALLS,2,6.00");
+        assert_eq!(lines[3], "Total,3,7.25");
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("plain"), "plain");
+        assert_eq!(quote("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[cfg(feature = "xlsx")]
+    #[test]
+    fn test_xlsx() {
+        let bytes = write_xlsx(&[table(), table()]).unwrap();
+        // XLSX is a zip archive
+        assert_eq!(&bytes[..2], b"PK");
+
+        let mut used = Vec::new();
+        assert_eq!(
+            unique_sheet_name("Doors/Windows", &mut used),
+            "DoorsWindows"
+        );
+        assert_eq!(
+            unique_sheet_name("doorswindows", &mut used),
+            "doorswindows (2)"
+        );
+    }
+}
@@ -0,0 +1,518 @@
+//! Formula expressions for derived quantities
+//!
+//! Formulas are ordinary arithmetic over named values:
+//!
+//! ```text
+//! area * thickness * 2400          // concrete mass from area
+//! ceil(length / 6.0)               // stock bars needed
+//! round(count * unit_price, 2)
+//! ```
+//!
+//! Supported: numbers, identifiers, `+ - * / ^`, unary minus, parentheses,
+//! and the functions `abs`, `sqrt`, `ceil`, `floor`, `round` (with optional
+//! decimals), `min`, `max`, and `if(cond, a, b)` (cond is non-zero).
+//!
+//! Numbers may carry a length unit, `mm`, `cm`, `m`, `in` or `ft`, as in
+//! `25.4mm * 3 + 1in`. Lengths are converted to the unit given to
+//! [`Formula::evaluate_in`]; this is the engine behind the document
+//! parameter table and numbers typed into command prompts and input fields.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{TakeoffError, TakeoffResult};
+use crate::io::units::Unit;
+
+/// Parsed formula
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Formula {
+    source: String,
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Length(f64, Unit),
+    Variable(String),
+    Negate(Box<Expr>),
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+impl Formula {
+    /// Parse a formula
+    pub fn parse(source: &str) -> TakeoffResult<Self> {
+        let tokens = tokenize(source).map_err(|message| TakeoffError::Formula {
+            formula: source.to_string(),
+            message,
+        })?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser
+            .expression()
+            .and_then(|expr| match parser.peek() {
+                None => Ok(expr),
+                Some(token) => Err(format!("unexpected '{}'", token)),
+            })
+            .map_err(|message| TakeoffError::Formula {
+                formula: source.to_string(),
+                message,
+            })?;
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    /// Formula text as written
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Names of the variables the formula reads
+    pub fn variables(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        collect_variables(&self.expr, &mut names);
+        names
+    }
+
+    /// Evaluate with the given variable values; lengths with units are an error
+    pub fn evaluate(&self, variables: &HashMap<String, f64>) -> TakeoffResult<f64> {
+        eval(&self.expr, variables, None)
+    }
+
+    /// Evaluate with the given variable values, converting lengths to `units`
+    pub fn evaluate_in(&self, variables: &HashMap<String, f64>, units: Unit) -> TakeoffResult<f64> {
+        eval(&self.expr, variables, Some(units))
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl TryFrom<String> for Formula {
+    type Error = TakeoffError;
+
+    fn try_from(source: String) -> TakeoffResult<Self> {
+        Formula::parse(&source)
+    }
+}
+
+impl From<Formula> for String {
+    fn from(formula: Formula) -> Self {
+        formula.source
+    }
+}
+
+fn collect_variables(expr: &Expr, names: &mut Vec<String>) {
+    match expr {
+        Expr::Number(_) | Expr::Length(..) => {}
+        Expr::Variable(name) => {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        Expr::Negate(inner) => collect_variables(inner, names),
+        Expr::Binary(a, _, b) => {
+            collect_variables(a, names);
+            collect_variables(b, names);
+        }
+        Expr::Call(_, args) => {
+            for arg in args {
+                collect_variables(arg, names);
+            }
+        }
+    }
+}
+
+fn eval(expr: &Expr, vars: &HashMap<String, f64>, units: Option<Unit>) -> TakeoffResult<f64> {
+    Ok(match expr {
+        Expr::Number(n) => *n,
+        Expr::Length(value, unit) => match units {
+            Some(target) => unit.convert_to(*value, target),
+            None => {
+                return Err(TakeoffError::Formula {
+                    formula: format!("{}{}", value, unit.abbreviation()),
+                    message: "lengths with units are not allowed here".to_string(),
+                })
+            }
+        },
+        Expr::Variable(name) => *vars
+            .get(name)
+            .ok_or_else(|| TakeoffError::UnknownVariable(name.clone()))?,
+        Expr::Negate(inner) => -eval(inner, vars, units)?,
+        Expr::Binary(a, op, b) => {
+            let (a, b) = (eval(a, vars, units)?, eval(b, vars, units)?);
+            match op {
+                BinaryOp::Add => a + b,
+                BinaryOp::Subtract => a - b,
+                BinaryOp::Multiply => a * b,
+                // Empty groups divide by zero; report 0 rather than inf/NaN
+                BinaryOp::Divide => {
+                    if b == 0.0 {
+                        0.0
+                    } else {
+                        a / b
+                    }
+                }
+                BinaryOp::Power => a.powf(b),
+            }
+        }
+        Expr::Call(name, args) => {
+            let values = args
+                .iter()
+                .map(|a| eval(a, vars, units))
+                .collect::<TakeoffResult<Vec<f64>>>()?;
+            call(name, &values)?
+        }
+    })
+}
+
+/// Unit of a length suffix
+fn length_unit(suffix: &str) -> Option<Unit> {
+    match suffix {
+        "mm" => Some(Unit::Millimeters),
+        "cm" => Some(Unit::Centimeters),
+        "m" => Some(Unit::Meters),
+        "in" => Some(Unit::Inches),
+        "ft" => Some(Unit::Feet),
+        _ => None,
+    }
+}
+
+fn call(name: &str, args: &[f64]) -> TakeoffResult<f64> {
+    let arity = |n: usize| {
+        if args.len() == n {
+            Ok(())
+        } else {
+            Err(TakeoffError::Formula {
+                formula: name.to_string(),
+                message: format!("{}() takes {} argument(s), got {}", name, n, args.len()),
+            })
+        }
+    };
+    Ok(match name {
+        "abs" => {
+            arity(1)?;
+            args[0].abs()
+        }
+        "sqrt" => {
+            arity(1)?;
+            args[0].sqrt()
+        }
+        "ceil" => {
+            arity(1)?;
+            args[0].ceil()
+        }
+        "floor" => {
+            arity(1)?;
+            args[0].floor()
+        }
+        "round" => {
+            if args.len() == 2 {
+                let scale = 10f64.powi(args[1] as i32);
+                (args[0] * scale).round() / scale
+            } else {
+                arity(1)?;
+                args[0].round()
+            }
+        }
+        "min" | "max" if !args.is_empty() => {
+            let pick = if name == "min" { f64::min } else { f64::max };
+            args[1..].iter().fold(args[0], |acc, v| pick(acc, *v))
+        }
+        "if" => {
+            arity(3)?;
+            if args[0] != 0.0 {
+                args[1]
+            } else {
+                args[2]
+            }
+        }
+        _ => return Err(TakeoffError::UnknownFunction(name.to_string())),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Ident(s) => write!(f, "{}", s),
+            Token::Op(c) => write!(f, "{}", c),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // Exponent: 1e3, 2.5E-2
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text
+                .parse()
+                .map_err(|_| format!("invalid number '{}'", text))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            tokens.push(match c {
+                '+' | '-' | '*' | '/' | '^' => Token::Op(c),
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                ',' => Token::Comma,
+                _ => return Err(format!("unexpected character '{}'", c)),
+            });
+            i += 1;
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive-descent parser; `^` binds tightest and is right-associative
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expression(&mut self) -> Result<Expr, String> {
+        let mut left = self.term()?;
+        while let Some(Token::Op(c @ ('+' | '-'))) = self.peek() {
+            let op = if *c == '+' {
+                BinaryOp::Add
+            } else {
+                BinaryOp::Subtract
+            };
+            self.pos += 1;
+            let right = self.term()?;
+            left = Expr::Binary(Box::new(left), op, Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        while let Some(Token::Op(c @ ('*' | '/'))) = self.peek() {
+            let op = if *c == '*' {
+                BinaryOp::Multiply
+            } else {
+                BinaryOp::Divide
+            };
+            self.pos += 1;
+            let right = self.unary()?;
+            left = Expr::Binary(Box::new(left), op, Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if let Some(Token::Op('-')) = self.peek() {
+            self.pos += 1;
+            return Ok(Expr::Negate(Box::new(self.unary()?)));
+        }
+        if let Some(Token::Op('+')) = self.peek() {
+            self.pos += 1;
+            return self.unary();
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.primary()?;
+        if let Some(Token::Op('^')) = self.peek() {
+            self.pos += 1;
+            let exponent = self.unary()?;
+            return Ok(Expr::Binary(
+                Box::new(base),
+                BinaryOp::Power,
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(n)) => {
+                let unit = match self.peek() {
+                    Some(Token::Ident(suffix)) => length_unit(suffix),
+                    _ => None,
+                };
+                match unit {
+                    Some(unit) => {
+                        self.pos += 1;
+                        Ok(Expr::Length(n, unit))
+                    }
+                    None => Ok(Expr::Number(n)),
+                }
+            }
+            Some(Token::Ident(name)) => {
+                if let Some(Token::LParen) = self.peek() {
+                    self.pos += 1;
+                    let mut args = Vec::new();
+                    if let Some(Token::RParen) = self.peek() {
+                        self.pos += 1;
+                    } else {
+                        loop {
+                            args.push(self.expression()?);
+                            match self.next() {
+                                Some(Token::Comma) => continue,
+                                Some(Token::RParen) => break,
+                                _ => return Err(format!("expected ')' after {}( arguments", name)),
+                            }
+                        }
+                    }
+                    Ok(Expr::Call(name, args))
+                } else {
+                    Ok(Expr::Variable(name))
+                }
+            }
+            Some(Token::LParen) => {
+                let inner = self.expression()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(inner),
+                    _ => Err("missing ')'".to_string()),
+                }
+            }
+            Some(token) => Err(format!("unexpected '{}'", token)),
+            None => Err("unexpected end of formula".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval_with(source: &str, vars: &[(&str, f64)]) -> TakeoffResult<f64> {
+        let vars = vars.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        Formula::parse(source)?.evaluate(&vars)
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_eq!(eval_with("1 + 2 * 3", &[]).unwrap(), 7.0);
+        assert_eq!(eval_with("(1 + 2) * 3", &[]).unwrap(), 9.0);
+        assert_eq!(eval_with("2 ^ 3 ^ 2", &[]).unwrap(), 512.0);
+        assert_eq!(eval_with("-2 ^ 2", &[]).unwrap(), -4.0);
+        assert_eq!(eval_with("10 / 4 - 1.5e0", &[]).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_variables_and_functions() {
+        let vars = [("length", 13.0), ("area", 2.0), ("thickness", 0.2)];
+        assert_eq!(eval_with("ceil(length / 6)", &vars).unwrap(), 3.0);
+        assert_eq!(eval_with("area * thickness * 2400", &vars).unwrap(), 960.0);
+        assert_eq!(eval_with("round(length / 3, 2)", &vars).unwrap(), 4.33);
+        assert_eq!(eval_with("max(1, length, area)", &vars).unwrap(), 13.0);
+        assert_eq!(eval_with("if(area - 2, 1, 5)", &vars).unwrap(), 5.0);
+        assert_eq!(eval_with("length / 0", &vars).unwrap(), 0.0);
+
+        let formula = Formula::parse("a * b + a").unwrap();
+        assert_eq!(formula.variables(), vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn test_lengths_with_units() {
+        let vars: HashMap<String, f64> = [("width".to_string(), 100.0)].into();
+        let in_mm = |source: &str| Formula::parse(source)?.evaluate_in(&vars, Unit::Millimeters);
+        assert!((in_mm("25.4mm*3 + 1in").unwrap() - 101.6).abs() < 1e-9);
+        assert!((in_mm("sqrt(2)*10").unwrap() - 2f64.sqrt() * 10.0).abs() < 1e-12);
+        assert!((in_mm("width + 2 cm - 0.001m").unwrap() - 119.0).abs() < 1e-9);
+        assert!((in_mm("1ft / 2").unwrap() - 152.4).abs() < 1e-9);
+
+        let feet = Formula::parse("18in + 6").unwrap().evaluate_in(&HashMap::new(), Unit::Feet).unwrap();
+        assert!((feet - 7.5).abs() < 1e-12);
+        assert!(matches!(eval_with("2mm", &[]), Err(TakeoffError::Formula { .. })));
+        assert!(Formula::parse("2 mm mm").is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            eval_with("width * 2", &[]),
+            Err(TakeoffError::UnknownVariable(name)) if name == "width"
+        ));
+        assert!(matches!(
+            eval_with("foo(1)", &[]),
+            Err(TakeoffError::UnknownFunction(_))
+        ));
+        assert!(Formula::parse("1 +").is_err());
+        assert!(Formula::parse("(1 + 2").is_err());
+        assert!(Formula::parse("1 $ 2").is_err());
+        assert!(Formula::parse("1 2").is_err());
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let formula = Formula::parse("area * 2").unwrap();
+        let json = serde_json::to_string(&formula).unwrap();
+        assert_eq!(json, "\"area * 2\"");
+        let back: Formula = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, formula);
+        assert!(serde_json::from_str::<Formula>("\"area *\"").is_err());
+    }
+}
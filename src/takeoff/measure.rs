@@ -0,0 +1,328 @@
+//! Per-entity quantities
+//!
+//! Every entity contributes a count of one. Curves add their length, and
+//! closed shapes (circles, ellipses, closed polylines, hatches) add their
+//! enclosed area. Block inserts add the quantities of the block's entities,
+//! scaled by the insert scale.
+
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::ops::{Add, AddAssign};
+
+use crate::geometry::{BSpline, NurbsCurve, Point2D};
+use crate::io::document::{Document, Ellipse, GeometryType, Polyline, Spline, Vec3};
+
+/// Nested block inserts deeper than this are not expanded
+const MAX_BLOCK_DEPTH: usize = 16;
+
+/// Measured quantities of one or more entities
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Quantities {
+    /// Number of entities
+    pub count: f64,
+    /// Total curve length (drawing units)
+    pub length: f64,
+    /// Total enclosed area (square drawing units)
+    pub area: f64,
+}
+
+impl Quantities {
+    /// Quantities of a single entity
+    pub fn new(length: f64, area: f64) -> Self {
+        Self {
+            count: 1.0,
+            length,
+            area,
+        }
+    }
+
+    /// Scale length by `factor` and area by its square
+    pub fn scaled(&self, factor: f64) -> Self {
+        Self {
+            count: self.count,
+            length: self.length * factor,
+            area: self.area * factor * factor,
+        }
+    }
+}
+
+impl Add for Quantities {
+    type Output = Quantities;
+
+    fn add(self, other: Quantities) -> Quantities {
+        Quantities {
+            count: self.count + other.count,
+            length: self.length + other.length,
+            area: self.area + other.area,
+        }
+    }
+}
+
+impl AddAssign for Quantities {
+    fn add_assign(&mut self, other: Quantities) {
+        *self = *self + other;
+    }
+}
+
+/// Measure one entity's geometry
+///
+/// `doc` supplies block definitions for inserts.
+pub fn measure(geometry: &GeometryType, doc: &Document) -> Quantities {
+    measure_at_depth(geometry, doc, 0)
+}
+
+fn measure_at_depth(geometry: &GeometryType, doc: &Document, depth: usize) -> Quantities {
+    match geometry {
+        GeometryType::Line(l) => Quantities::new(distance(l.start, l.end), 0.0),
+        GeometryType::Circle(c) => Quantities::new(2.0 * PI * c.radius, PI * c.radius * c.radius),
+        GeometryType::Arc(a) => {
+            let mut sweep = a.end_angle - a.start_angle;
+            while sweep <= 0.0 {
+                sweep += 2.0 * PI;
+            }
+            Quantities::new(a.radius * sweep, 0.0)
+        }
+        GeometryType::Ellipse(e) => {
+            Quantities::new(ellipse_perimeter(e), PI * e.major_axis * e.minor_axis)
+        }
+        GeometryType::Polyline(p) => polyline_quantities(p),
+        GeometryType::Spline(s) => Quantities::new(spline_length(s), 0.0),
+        GeometryType::Hatch(h) => {
+            // First boundary is the outer loop, the rest are holes
+            let mut areas = h.boundaries.iter().map(|b| shoelace(b).abs());
+            let outer = areas.next().unwrap_or(0.0);
+            let holes: f64 = areas.sum();
+            Quantities::new(0.0, (outer - holes).max(0.0))
+        }
+        GeometryType::Insert(insert) => {
+            let mut total = Quantities::new(0.0, 0.0);
+            if depth < MAX_BLOCK_DEPTH {
+                if let Some(block) = doc.get_block(&insert.block_name) {
+                    let mut contents = Quantities::default();
+                    for entity in &block.entities {
+                        contents += measure_at_depth(&entity.geometry, doc, depth + 1);
+                    }
+                    // Non-uniform scale: use the geometric mean for lengths
+                    let factor = (insert.scale.x * insert.scale.y).abs().sqrt();
+                    let contents = contents.scaled(factor);
+                    total.length = contents.length;
+                    total.area = contents.area;
+                }
+            }
+            total
+        }
+        GeometryType::Point(_)
+        | GeometryType::Text(_)
+        | GeometryType::MText(_)
+        | GeometryType::Dimension(_)
+        | GeometryType::PointCloud(_) => Quantities::new(0.0, 0.0),
+    }
+}
+
+fn distance(a: Vec3, b: Vec3) -> f64 {
+    ((b.x - a.x).powi(2) + (b.y - a.y).powi(2) + (b.z - a.z).powi(2)).sqrt()
+}
+
+/// Ramanujan's second approximation (error well below 0.01% for CAD use)
+fn ellipse_perimeter(e: &Ellipse) -> f64 {
+    let (a, b) = (e.major_axis, e.minor_axis);
+    if a + b <= 0.0 {
+        return 0.0;
+    }
+    let h = ((a - b) / (a + b)).powi(2);
+    PI * (a + b) * (1.0 + 3.0 * h / (10.0 + (4.0 - 3.0 * h).sqrt()))
+}
+
+/// Length and (if closed) area, treating bulged segments as arcs
+fn polyline_quantities(p: &Polyline) -> Quantities {
+    let n = p.vertices.len();
+    if n < 2 {
+        return Quantities::new(0.0, 0.0);
+    }
+    let segments = if p.closed { n } else { n - 1 };
+    let mut length = 0.0;
+    let mut twice_area = 0.0;
+    for i in 0..segments {
+        let a = p.vertices[i].position;
+        let b = p.vertices[(i + 1) % n].position;
+        let chord = distance(a, b);
+        let bulge = p.vertices[i].bulge;
+        twice_area += a.x * b.y - b.x * a.y;
+        if bulge.abs() < 1e-10 || chord < 1e-12 {
+            length += chord;
+        } else {
+            // Included angle 4·atan(bulge); the circular segment between
+            // chord and arc adds (r²/2)(θ - sin θ), signed with the bulge
+            let theta = 4.0 * bulge.atan();
+            let radius = chord / (2.0 * (theta / 2.0).sin()).abs();
+            length += radius * theta.abs();
+            twice_area += radius * radius * (theta - theta.sin());
+        }
+    }
+    let area = if p.closed {
+        twice_area.abs() / 2.0
+    } else {
+        0.0
+    };
+    Quantities::new(length, area)
+}
+
+fn shoelace(points: &[Vec3]) -> f64 {
+    let n = points.len();
+    if n < 3 {
+        return 0.0;
+    }
+    (0..n)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+/// Spline length by dense sampling; falls back to the control polygon when
+/// the knot vector is unusable
+fn spline_length(s: &Spline) -> f64 {
+    let points: Vec<Point2D> = s
+        .control_points
+        .iter()
+        .map(|p| Point2D::new(p.x, p.y))
+        .collect();
+    let samples = (points.len() * 32).max(64);
+
+    let curve = match &s.weights {
+        Some(weights) => {
+            NurbsCurve::new(points.clone(), weights.clone(), s.knots.clone(), s.degree)
+                .map(|c| c.to_polyline(samples))
+        }
+        None if s.knots.is_empty() => {
+            BSpline::clamped(points.clone(), s.degree).map(|c| c.to_polyline(samples))
+        }
+        None => {
+            BSpline::new(points.clone(), s.knots.clone(), s.degree).map(|c| c.to_polyline(samples))
+        }
+    };
+    let polyline = curve.unwrap_or(points);
+    polyline.windows(2).map(|w| w[0].distance_to(&w[1])).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{Block, Circle, Entity, Insert, Line, Vertex};
+    use std::collections::HashMap;
+
+    fn vertex(x: f64, y: f64, bulge: f64) -> Vertex {
+        Vertex {
+            position: Vec3::new(x, y, 0.0),
+            bulge,
+        }
+    }
+
+    #[test]
+    fn test_basic_shapes() {
+        let doc = Document::new();
+        let line = GeometryType::Line(Line {
+            start: Vec3::new(0.0, 0.0, 0.0),
+            end: Vec3::new(3.0, 4.0, 0.0),
+        });
+        assert_eq!(measure(&line, &doc), Quantities::new(5.0, 0.0));
+
+        let circle = GeometryType::Circle(Circle {
+            center: Vec3::zero(),
+            radius: 2.0,
+            normal: Vec3::unit_z(),
+        });
+        let q = measure(&circle, &doc);
+        assert!((q.length - 4.0 * PI).abs() < 1e-12);
+        assert!((q.area - 4.0 * PI).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_polyline_with_bulges() {
+        // 10x10 square
+        let square = Polyline {
+            vertices: vec![
+                vertex(0.0, 0.0, 0.0),
+                vertex(10.0, 0.0, 0.0),
+                vertex(10.0, 10.0, 0.0),
+                vertex(0.0, 10.0, 0.0),
+            ],
+            closed: true,
+        };
+        assert_eq!(polyline_quantities(&square), Quantities::new(40.0, 100.0));
+
+        // Slot: two straight sides joined by semicircles of radius 5
+        let slot = Polyline {
+            vertices: vec![
+                vertex(0.0, 0.0, 0.0),
+                vertex(20.0, 0.0, 1.0),
+                vertex(20.0, 10.0, 0.0),
+                vertex(0.0, 10.0, 1.0),
+            ],
+            closed: true,
+        };
+        let q = polyline_quantities(&slot);
+        assert!((q.length - (40.0 + 10.0 * PI)).abs() < 1e-9);
+        assert!((q.area - (200.0 + 25.0 * PI)).abs() < 1e-9);
+
+        let open = Polyline {
+            closed: false,
+            ..square
+        };
+        assert_eq!(polyline_quantities(&open), Quantities::new(30.0, 0.0));
+    }
+
+    #[test]
+    fn test_insert_scales_block_contents() {
+        let mut doc = Document::new();
+        doc.add_block(Block {
+            name: "DOOR".to_string(),
+            base_point: Vec3::zero(),
+            entities: vec![Entity::new(
+                GeometryType::Line(Line {
+                    start: Vec3::zero(),
+                    end: Vec3::new(1.0, 0.0, 0.0),
+                }),
+                "0".to_string(),
+            )],
+            description: String::new(),
+        });
+        let insert = GeometryType::Insert(Insert {
+            block_name: "DOOR".to_string(),
+            position: Vec3::zero(),
+            scale: Vec3::new(2.0, 2.0, 1.0),
+            rotation: 0.0,
+            attributes: HashMap::new(),
+        });
+        assert_eq!(measure(&insert, &doc), Quantities::new(2.0, 0.0));
+    }
+
+    #[test]
+    fn test_ellipse_and_spline() {
+        let doc = Document::new();
+        let ellipse = GeometryType::Ellipse(Ellipse {
+            center: Vec3::zero(),
+            major_axis: 3.0,
+            minor_axis: 3.0,
+            rotation: 0.0,
+            normal: Vec3::unit_z(),
+        });
+        assert!((measure(&ellipse, &doc).length - 6.0 * PI).abs() < 1e-9);
+
+        // Degree-1 spline is its control polygon
+        let spline = GeometryType::Spline(Spline {
+            degree: 1,
+            control_points: vec![
+                Vec3::zero(),
+                Vec3::new(3.0, 0.0, 0.0),
+                Vec3::new(3.0, 4.0, 0.0),
+            ],
+            knots: Vec::new(),
+            weights: None,
+            closed: false,
+        });
+        assert!((measure(&spline, &doc).length - 7.0).abs() < 0.05);
+    }
+}
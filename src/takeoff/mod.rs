@@ -0,0 +1,85 @@
+//! # CADDY Quantity Takeoff
+//!
+//! Measures drawings for estimating:
+//!
+//! - **Measurement**: count, length, and area per entity, including bulged
+//!   polylines, hatches with holes, and block inserts
+//! - **Schedules**: entities filtered and grouped by layer, block, type, or
+//!   attribute, with count/length/area columns
+//! - **Formulas**: derived columns such as `ceil(length / 6) * price`, reading
+//!   measured quantities, constants, and earlier columns
+//! - **Live tables**: schedules re-evaluated as the drawing changes
+//! - **Export**: CSV, and XLSX with the `xlsx` feature
+//!
+//! ## Example
+//!
+//! ```
+//! use caddy::io::document::{Document, Entity, GeometryType, Line, Vec3};
+//! use caddy::takeoff::{Column, GroupBy, ScheduleDefinition};
+//!
+//! let mut doc = Document::new();
+//! doc.add_entity(Entity::new(
+//!     GeometryType::Line(Line {
+//!         start: Vec3::zero(),
+//!         end: Vec3::new(7.5, 0.0, 0.0),
+//!     }),
+//!     "WALLS".to_string(),
+//! ));
+//!
+//! let table = ScheduleDefinition::new("Walls")
+//!     .group_by(GroupBy::Layer)
+//!     .column(Column::length("length_m"))
+//!     .column(Column::formula("cost", "length_m * rate").unwrap())
+//!     .constant("rate", 40.0)
+//!     .evaluate(&doc)
+//!     .unwrap();
+//! assert_eq!(table.value(&["WALLS"], "cost"), Some(300.0));
+//! ```
+
+pub mod export;
+pub mod formula;
+pub mod measure;
+pub mod schedule;
+
+use thiserror::Error;
+
+#[cfg(feature = "xlsx")]
+pub use export::write_xlsx;
+pub use formula::Formula;
+pub use measure::{measure, Quantities};
+pub use schedule::{
+    Column, ColumnSource, EntityFilter, GroupBy, LiveSchedule, ScheduleDefinition, ScheduleRow,
+    ScheduleTable,
+};
+
+/// Takeoff errors
+#[derive(Debug, Error)]
+pub enum TakeoffError {
+    /// Formula could not be parsed or evaluated
+    #[error("Formula error in '{formula}': {message}")]
+    Formula {
+        /// Formula source
+        formula: String,
+        /// What went wrong
+        message: String,
+    },
+
+    /// Formula reads a variable that is not defined
+    #[error("Unknown variable: {0}")]
+    UnknownVariable(String),
+
+    /// Formula calls a function that does not exist
+    #[error("Unknown function: {0}")]
+    UnknownFunction(String),
+
+    /// Column name clashes with a built-in quantity, constant, or column
+    #[error("Duplicate name: {0}")]
+    DuplicateName(String),
+
+    /// Writing an export format failed
+    #[error("Export failed: {0}")]
+    Export(String),
+}
+
+/// Result type for takeoff operations
+pub type TakeoffResult<T> = Result<T, TakeoffError>;
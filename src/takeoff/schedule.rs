@@ -0,0 +1,653 @@
+//! Schedule definitions and evaluated tables
+//!
+//! A schedule filters the drawing, groups entities by one or more keys
+//! (layer, block, entity type, attribute, classification), and reports a column per
+//! quantity. Base columns read the measured count, length, or area; formula
+//! columns derive further quantities from those, from named constants, and
+//! from earlier columns.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use super::formula::Formula;
+use super::measure::{measure, Quantities};
+use super::{TakeoffError, TakeoffResult};
+use crate::io::document::{Document, Entity, GeometryType};
+use crate::io::tags;
+use crate::standards::matches_pattern;
+
+/// Grouping key
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GroupBy {
+    /// Entity layer
+    Layer,
+    /// Block name of inserts (empty for other entities)
+    Block,
+    /// Entity type name (Line, Circle, ...)
+    EntityType,
+    /// Value of a named attribute (entity or insert attribute)
+    Attribute(String),
+    /// Classification cut to this many levels (empty when unclassified)
+    Classification(usize),
+}
+
+impl GroupBy {
+    /// Column header for the key
+    pub fn header(&self) -> String {
+        match self {
+            GroupBy::Layer => "Layer".to_string(),
+            GroupBy::Block => "Block".to_string(),
+            GroupBy::EntityType => "Type".to_string(),
+            GroupBy::Attribute(name) => name.clone(),
+            GroupBy::Classification(_) => "Classification".to_string(),
+        }
+    }
+
+    /// Key value for an entity
+    pub fn key(&self, entity: &Entity) -> String {
+        match self {
+            GroupBy::Layer => entity.layer.clone(),
+            GroupBy::Block => match &entity.geometry {
+                GeometryType::Insert(insert) => insert.block_name.clone(),
+                _ => String::new(),
+            },
+            GroupBy::EntityType => entity.geometry.type_name().to_string(),
+            GroupBy::Attribute(name) => attribute(entity, name).unwrap_or_default(),
+            GroupBy::Classification(depth) => tags::classification_level(entity, *depth).unwrap_or_default(),
+        }
+    }
+}
+
+fn attribute(entity: &Entity, name: &str) -> Option<String> {
+    entity
+        .attributes
+        .get(name)
+        .cloned()
+        .or_else(|| match &entity.geometry {
+            GeometryType::Insert(insert) => insert.attributes.get(name).cloned(),
+            _ => None,
+        })
+}
+
+/// Which entities a schedule includes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntityFilter {
+    /// Layers to include (empty = all)
+    pub layers: Vec<String>,
+    /// Entity type names to include (empty = all)
+    pub entity_types: Vec<String>,
+    /// Block names to include (empty = all entities, not only inserts)
+    pub blocks: Vec<String>,
+    /// Attribute name/value pairs that must all match
+    pub attributes: Vec<(String, String)>,
+    /// Tag patterns that must all match one of the entity's tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Include invisible entities and entities on hidden or frozen layers
+    pub include_hidden: bool,
+}
+
+impl EntityFilter {
+    /// Whether `entity` passes the filter
+    pub fn matches(&self, entity: &Entity, doc: &Document) -> bool {
+        if !self.include_hidden {
+            let layer_hidden = doc
+                .get_layer(&entity.layer)
+                .is_some_and(|l| !l.visible || l.frozen);
+            if !entity.visible || layer_hidden {
+                return false;
+            }
+        }
+        if !self.layers.is_empty() && !self.layers.contains(&entity.layer) {
+            return false;
+        }
+        let type_name = entity.geometry.type_name();
+        if !self.entity_types.is_empty() && !self.entity_types.iter().any(|t| t == type_name) {
+            return false;
+        }
+        if !self.blocks.is_empty() {
+            let block = GroupBy::Block.key(entity);
+            if !self.blocks.contains(&block) {
+                return false;
+            }
+        }
+        let entity_tags = tags::tags(entity);
+        if !self
+            .tags
+            .iter()
+            .all(|pattern| entity_tags.iter().any(|t| matches_pattern(pattern, t)))
+        {
+            return false;
+        }
+        self.attributes
+            .iter()
+            .all(|(name, value)| attribute(entity, name).as_deref() == Some(value.as_str()))
+    }
+}
+
+/// Where a column's values come from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ColumnSource {
+    /// Number of entities
+    Count,
+    /// Total length
+    Length,
+    /// Total area
+    Area,
+    /// Derived from other quantities
+    Formula(Formula),
+}
+
+/// Schedule column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Column {
+    /// Identifier used by formulas
+    pub name: String,
+    /// Display header
+    pub header: String,
+    /// Value source
+    pub source: ColumnSource,
+    /// Decimal places shown in exports
+    pub decimals: usize,
+    /// Unit label shown after the header
+    pub unit: Option<String>,
+}
+
+impl Column {
+    fn new(name: &str, source: ColumnSource, decimals: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            header: name.to_string(),
+            source,
+            decimals,
+            unit: None,
+        }
+    }
+
+    /// Entity count column
+    pub fn count(name: &str) -> Self {
+        Self::new(name, ColumnSource::Count, 0)
+    }
+
+    /// Total length column
+    pub fn length(name: &str) -> Self {
+        Self::new(name, ColumnSource::Length, 2)
+    }
+
+    /// Total area column
+    pub fn area(name: &str) -> Self {
+        Self::new(name, ColumnSource::Area, 2)
+    }
+
+    /// Derived column
+    pub fn formula(name: &str, formula: &str) -> TakeoffResult<Self> {
+        Ok(Self::new(
+            name,
+            ColumnSource::Formula(Formula::parse(formula)?),
+            2,
+        ))
+    }
+
+    /// Set the display header
+    pub fn with_header(mut self, header: &str) -> Self {
+        self.header = header.to_string();
+        self
+    }
+
+    /// Set the decimal places
+    pub fn with_decimals(mut self, decimals: usize) -> Self {
+        self.decimals = decimals;
+        self
+    }
+
+    /// Set the unit label
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.to_string());
+        self
+    }
+
+    /// Header with the unit appended, e.g. `Area (m²)`
+    pub fn display_header(&self) -> String {
+        match &self.unit {
+            Some(unit) => format!("{} ({})", self.header, unit),
+            None => self.header.clone(),
+        }
+    }
+
+    /// Format a value with the column's precision
+    pub fn format(&self, value: f64) -> String {
+        format!("{:.*}", self.decimals, value)
+    }
+}
+
+/// Schedule definition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleDefinition {
+    /// Schedule name
+    pub name: String,
+    /// Grouping keys, outermost first; no keys gives a single row
+    pub group_by: Vec<GroupBy>,
+    /// Entity filter
+    pub filter: EntityFilter,
+    /// Columns in display order
+    pub columns: Vec<Column>,
+    /// Named constants available to formulas (rates, thicknesses, prices)
+    pub constants: BTreeMap<String, f64>,
+    /// Drawing units to schedule units; lengths scale by this, areas by its square
+    pub unit_scale: f64,
+}
+
+impl ScheduleDefinition {
+    /// Create an empty schedule
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            group_by: Vec::new(),
+            filter: EntityFilter::default(),
+            columns: Vec::new(),
+            constants: BTreeMap::new(),
+            unit_scale: 1.0,
+        }
+    }
+
+    /// Add a grouping key
+    pub fn group_by(mut self, key: GroupBy) -> Self {
+        self.group_by.push(key);
+        self
+    }
+
+    /// Add a column
+    pub fn column(mut self, column: Column) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// Set the entity filter
+    pub fn filter(mut self, filter: EntityFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Define a formula constant
+    pub fn constant(mut self, name: &str, value: f64) -> Self {
+        self.constants.insert(name.to_string(), value);
+        self
+    }
+
+    /// Set the unit scale
+    pub fn unit_scale(mut self, scale: f64) -> Self {
+        self.unit_scale = scale;
+        self
+    }
+
+    /// Check column names and that every formula only reads known values
+    pub fn validate(&self) -> TakeoffResult<()> {
+        let mut known: Vec<&str> = vec!["count", "length", "area"];
+        known.extend(self.constants.keys().map(String::as_str));
+        for column in &self.columns {
+            if known.contains(&column.name.as_str()) {
+                return Err(TakeoffError::DuplicateName(column.name.clone()));
+            }
+            if let ColumnSource::Formula(formula) = &column.source {
+                // Formulas may only read columns defined before them
+                if let Some(missing) = formula
+                    .variables()
+                    .into_iter()
+                    .find(|v| !known.contains(&v.as_str()))
+                {
+                    return Err(TakeoffError::UnknownVariable(missing));
+                }
+            }
+            known.push(&column.name);
+        }
+        Ok(())
+    }
+
+    /// Evaluate the schedule against a drawing
+    pub fn evaluate(&self, doc: &Document) -> TakeoffResult<ScheduleTable> {
+        self.validate()?;
+
+        let mut groups: BTreeMap<Vec<String>, (Quantities, Vec<Uuid>)> = BTreeMap::new();
+        for entity in doc.entities.iter().filter(|e| self.filter.matches(e, doc)) {
+            let keys = self.group_by.iter().map(|g| g.key(entity)).collect();
+            let group = groups.entry(keys).or_default();
+            group.0 += measure(&entity.geometry, doc).scaled(self.unit_scale);
+            group.1.push(entity.id);
+        }
+
+        let mut rows = Vec::with_capacity(groups.len());
+        for (keys, (quantities, entities)) in groups {
+            let values = self.row_values(&quantities)?;
+            rows.push(ScheduleRow {
+                keys,
+                quantities,
+                values,
+                entities,
+            });
+        }
+
+        let totals = (0..self.columns.len())
+            .map(|i| rows.iter().map(|r| r.values[i]).sum())
+            .collect();
+
+        Ok(ScheduleTable {
+            name: self.name.clone(),
+            group_headers: self.group_by.iter().map(GroupBy::header).collect(),
+            columns: self.columns.clone(),
+            rows,
+            totals,
+        })
+    }
+
+    fn row_values(&self, q: &Quantities) -> TakeoffResult<Vec<f64>> {
+        let mut vars: HashMap<String, f64> = self
+            .constants
+            .iter()
+            .map(|(k, v)| (k.clone(), *v))
+            .collect();
+        vars.insert("count".to_string(), q.count);
+        vars.insert("length".to_string(), q.length);
+        vars.insert("area".to_string(), q.area);
+
+        let mut values = Vec::with_capacity(self.columns.len());
+        for column in &self.columns {
+            let value = match &column.source {
+                ColumnSource::Count => q.count,
+                ColumnSource::Length => q.length,
+                ColumnSource::Area => q.area,
+                ColumnSource::Formula(formula) => formula.evaluate(&vars)?,
+            };
+            vars.insert(column.name.clone(), value);
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+/// One group of an evaluated schedule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleRow {
+    /// Group key values, in `group_by` order
+    pub keys: Vec<String>,
+    /// Measured quantities of the group (in schedule units)
+    pub quantities: Quantities,
+    /// Column values, in column order
+    pub values: Vec<f64>,
+    /// Entities in the group
+    pub entities: Vec<Uuid>,
+}
+
+/// Evaluated schedule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleTable {
+    /// Schedule name
+    pub name: String,
+    /// Headers of the key columns
+    pub group_headers: Vec<String>,
+    /// Value columns
+    pub columns: Vec<Column>,
+    /// Rows sorted by key
+    pub rows: Vec<ScheduleRow>,
+    /// Column totals (sum of row values)
+    pub totals: Vec<f64>,
+}
+
+impl ScheduleTable {
+    /// Index of a column by name
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c.name == name)
+    }
+
+    /// Row with the given key values
+    pub fn row(&self, keys: &[&str]) -> Option<&ScheduleRow> {
+        self.rows
+            .iter()
+            .find(|r| r.keys.iter().map(String::as_str).eq(keys.iter().copied()))
+    }
+
+    /// Value of `column` in the row with the given keys
+    pub fn value(&self, keys: &[&str], column: &str) -> Option<f64> {
+        let index = self.column_index(column)?;
+        self.row(keys).map(|r| r.values[index])
+    }
+
+    /// Total of `column`
+    pub fn total(&self, column: &str) -> Option<f64> {
+        self.column_index(column).map(|i| self.totals[i])
+    }
+}
+
+/// Schedule kept in step with a changing drawing
+///
+/// Call [`LiveSchedule::refresh`] after edits; the table is re-evaluated
+/// and the return value says whether anything visible changed.
+#[derive(Debug, Clone)]
+pub struct LiveSchedule {
+    definition: ScheduleDefinition,
+    table: ScheduleTable,
+}
+
+impl LiveSchedule {
+    /// Evaluate `definition` against `doc`
+    pub fn new(definition: ScheduleDefinition, doc: &Document) -> TakeoffResult<Self> {
+        let table = definition.evaluate(doc)?;
+        Ok(Self { definition, table })
+    }
+
+    /// Schedule definition
+    pub fn definition(&self) -> &ScheduleDefinition {
+        &self.definition
+    }
+
+    /// Current table
+    pub fn table(&self) -> &ScheduleTable {
+        &self.table
+    }
+
+    /// Replace the definition and re-evaluate
+    pub fn set_definition(
+        &mut self,
+        definition: ScheduleDefinition,
+        doc: &Document,
+    ) -> TakeoffResult<()> {
+        self.table = definition.evaluate(doc)?;
+        self.definition = definition;
+        Ok(())
+    }
+
+    /// Re-evaluate; returns `true` when the table changed
+    pub fn refresh(&mut self, doc: &Document) -> TakeoffResult<bool> {
+        let table = self.definition.evaluate(doc)?;
+        let changed = table != self.table;
+        self.table = table;
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::document::{Circle, Insert, Line, Polyline, Vec3, Vertex};
+
+    fn line(doc: &mut Document, layer: &str, length: f64) -> Uuid {
+        doc.add_entity(Entity::new(
+            GeometryType::Line(Line {
+                start: Vec3::zero(),
+                end: Vec3::new(length, 0.0, 0.0),
+            }),
+            layer.to_string(),
+        ))
+    }
+
+    fn slab(doc: &mut Document, layer: &str, w: f64, h: f64, grade: &str) {
+        let v = |x, y| Vertex {
+            position: Vec3::new(x, y, 0.0),
+            bulge: 0.0,
+        };
+        let mut entity = Entity::new(
+            GeometryType::Polyline(Polyline {
+                vertices: vec![v(0.0, 0.0), v(w, 0.0), v(w, h), v(0.0, h)],
+                closed: true,
+            }),
+            layer.to_string(),
+        );
+        entity
+            .attributes
+            .insert("GRADE".to_string(), grade.to_string());
+        doc.add_entity(entity);
+    }
+
+    #[test]
+    fn test_group_by_layer_with_formula() {
+        let mut doc = Document::new();
+        line(&mut doc, "WALLS", 4.0);
+        line(&mut doc, "WALLS", 6.5);
+        line(&mut doc, "PIPES", 10.0);
+
+        let schedule = ScheduleDefinition::new("Linear")
+            .group_by(GroupBy::Layer)
+            .column(Column::count("count_"))
+            .column(Column::length("len"))
+            .column(Column::formula("bars", "ceil(len / bar_length)").unwrap())
+            .column(Column::formula("cost", "bars * price").unwrap())
+            .constant("bar_length", 6.0)
+            .constant("price", 12.5);
+        let table = schedule.evaluate(&doc).unwrap();
+
+        assert_eq!(table.group_headers, vec!["Layer".to_string()]);
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[0].keys, vec!["PIPES".to_string()]);
+        assert_eq!(table.value(&["WALLS"], "count_"), Some(2.0));
+        assert_eq!(table.value(&["WALLS"], "len"), Some(10.5));
+        assert_eq!(table.value(&["WALLS"], "bars"), Some(2.0));
+        assert_eq!(table.value(&["PIPES"], "cost"), Some(25.0));
+        assert_eq!(table.total("cost"), Some(50.0));
+        assert_eq!(table.total("len"), Some(20.5));
+    }
+
+    #[test]
+    fn test_attribute_grouping_and_filter() {
+        let mut doc = Document::new();
+        slab(&mut doc, "SLABS", 2000.0, 3000.0, "C30");
+        slab(&mut doc, "SLABS", 1000.0, 1000.0, "C30");
+        slab(&mut doc, "SLABS", 4000.0, 1000.0, "C40");
+        slab(&mut doc, "OTHER", 9000.0, 9000.0, "C30");
+
+        let schedule = ScheduleDefinition::new("Concrete")
+            .filter(EntityFilter {
+                layers: vec!["SLABS".to_string()],
+                ..Default::default()
+            })
+            .group_by(GroupBy::Attribute("GRADE".to_string()))
+            .column(Column::area("m2").with_unit("m²"))
+            .column(Column::formula("volume", "m2 * 0.2").unwrap())
+            .unit_scale(0.001);
+        let table = schedule.evaluate(&doc).unwrap();
+
+        assert_eq!(table.rows.len(), 2);
+        assert!((table.value(&["C30"], "m2").unwrap() - 7.0).abs() < 1e-9);
+        assert!((table.value(&["C40"], "volume").unwrap() - 0.8).abs() < 1e-9);
+        assert_eq!(table.columns[0].display_header(), "m2 (m²)");
+    }
+
+    #[test]
+    fn test_classification_grouping_and_tag_filter() {
+        let mut doc = Document::new();
+        for (length, class, tag) in [
+            (2.0, "Structural/Steel/Beam", "phase-1"),
+            (3.0, "Structural/Steel/Column", "phase-2"),
+            (4.0, "Structural/Concrete", "phase-2"),
+            (5.0, "Services/Pipe", "phase-2"),
+        ] {
+            let id = line(&mut doc, "0", length);
+            let entity = doc.get_entity_mut(id).unwrap();
+            tags::set_classification(entity, Some(class)).unwrap();
+            tags::add_tag(entity, tag).unwrap();
+        }
+
+        let schedule = ScheduleDefinition::new("Phase 2")
+            .filter(EntityFilter {
+                tags: vec!["phase-2".to_string()],
+                ..Default::default()
+            })
+            .group_by(GroupBy::Classification(2))
+            .column(Column::length("len"));
+        let table = schedule.evaluate(&doc).unwrap();
+
+        assert_eq!(table.rows.len(), 3);
+        assert_eq!(table.value(&["Structural/Steel"], "len"), Some(3.0));
+        assert_eq!(table.value(&["Services/Pipe"], "len"), Some(5.0));
+    }
+
+    #[test]
+    fn test_block_counts() {
+        let mut doc = Document::new();
+        for name in ["CHAIR", "CHAIR", "DESK"] {
+            doc.add_entity(Entity::new(
+                GeometryType::Insert(Insert {
+                    block_name: name.to_string(),
+                    position: Vec3::zero(),
+                    scale: Vec3::new(1.0, 1.0, 1.0),
+                    rotation: 0.0,
+                    attributes: Default::default(),
+                }),
+                "FURNITURE".to_string(),
+            ));
+        }
+        doc.add_entity(Entity::new(
+            GeometryType::Circle(Circle {
+                center: Vec3::zero(),
+                radius: 1.0,
+                normal: Vec3::unit_z(),
+            }),
+            "FURNITURE".to_string(),
+        ));
+
+        let table = ScheduleDefinition::new("Furniture")
+            .filter(EntityFilter {
+                entity_types: vec!["Insert".to_string()],
+                ..Default::default()
+            })
+            .group_by(GroupBy::Block)
+            .column(Column::count("qty"))
+            .evaluate(&doc)
+            .unwrap();
+        assert_eq!(table.value(&["CHAIR"], "qty"), Some(2.0));
+        assert_eq!(table.value(&["DESK"], "qty"), Some(1.0));
+        assert_eq!(table.rows.len(), 2);
+    }
+
+    #[test]
+    fn test_validation() {
+        let forward = ScheduleDefinition::new("bad")
+            .column(Column::formula("a", "b * 2").unwrap())
+            .column(Column::count("b"));
+        assert!(matches!(forward.validate(), Err(TakeoffError::UnknownVariable(v)) if v == "b"));
+
+        let duplicate = ScheduleDefinition::new("dup").column(Column::count("area"));
+        assert!(matches!(
+            duplicate.validate(),
+            Err(TakeoffError::DuplicateName(_))
+        ));
+    }
+
+    #[test]
+    fn test_live_schedule_refresh() {
+        let mut doc = Document::new();
+        let id = line(&mut doc, "WALLS", 4.0);
+        let definition = ScheduleDefinition::new("Walls")
+            .group_by(GroupBy::Layer)
+            .column(Column::length("len"));
+        let mut live = LiveSchedule::new(definition, &doc).unwrap();
+
+        assert!(!live.refresh(&doc).unwrap());
+        line(&mut doc, "WALLS", 2.0);
+        assert!(live.refresh(&doc).unwrap());
+        assert_eq!(live.table().total("len"), Some(6.0));
+
+        doc.remove_entity(id);
+        assert!(live.refresh(&doc).unwrap());
+        assert_eq!(live.table().total("len"), Some(2.0));
+    }
+}
@@ -501,9 +501,48 @@ impl ActivityManager {
         )?;
 
         let index = *self.activity_index.get(&activity.id).unwrap();
-        self.activities[index].changes = Some(ActivityChanges { before, after });
+        let changes = ActivityChanges { before, after };
+        self.activities[index].changes = Some(changes.clone());
 
-        Ok(activity)
+        // Security events were audited before the changes were attached
+        if let Some(audit) = self
+            .audit_entries
+            .iter_mut()
+            .rev()
+            .find(|e| e.activity_id == activity.id)
+        {
+            audit.changes = Some(changes);
+        }
+
+        Ok(self.activities[index].clone())
+    }
+
+    /// Record an audit entry for a logged activity, or add compliance tags
+    /// to the one it already has
+    pub fn audit_activity(&mut self, activity_id: &str, compliance_tags: &[&str]) -> ActivityResult<()> {
+        let index = *self
+            .activity_index
+            .get(activity_id)
+            .ok_or_else(|| ActivityError::NotFound(activity_id.to_string()))?;
+
+        if !self.audit_entries.iter().any(|e| e.activity_id == activity_id) {
+            let activity = self.activities[index].clone();
+            self.create_audit_entry(&activity)?;
+        }
+
+        let audit = self
+            .audit_entries
+            .iter_mut()
+            .rev()
+            .find(|e| e.activity_id == activity_id)
+            .unwrap();
+        for tag in compliance_tags {
+            if !audit.compliance_tags.iter().any(|t| t == tag) {
+                audit.compliance_tags.push(tag.to_string());
+            }
+        }
+
+        Ok(())
     }
 
     /// Create audit entry
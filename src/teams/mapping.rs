@@ -0,0 +1,690 @@
+//! Identity Provider Group Mapping Module
+//!
+//! Maps identity provider groups and claims onto workspace memberships:
+//! - Mapping rules from SAML/OIDC groups or claim values to a workspace,
+//!   role and team
+//! - Membership sync at login and on SCIM pushes
+//! - Dry-run previews of the changes a sync would make
+//! - Audit trail entries for every membership change a sync makes
+//!
+//! Rules only manage memberships they created. Those carry the
+//! [`DIRECTORY_MANAGED_KEY`] metadata marker; memberships added by hand are
+//! never changed or removed by a sync.
+
+use super::activity::{ActivityError, ActivityManager, ActivityType};
+use super::members::{Member, MemberError, MemberManager, MemberRole};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+
+/// Member metadata key marking memberships owned by group mapping
+pub const DIRECTORY_MANAGED_KEY: &str = "directory_managed";
+
+/// Actor recorded on activities a sync logs
+const SYNC_ACTOR: &str = "identity-provider";
+
+/// Compliance tag on audit entries a sync writes
+const SYNC_AUDIT_TAG: &str = "identity-sync";
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+#[derive(Error, Debug)]
+pub enum GroupMappingError {
+    #[error("Invalid mapping rule: {0}")]
+    InvalidRule(String),
+
+    #[error("Mapping rule already exists: {0}")]
+    DuplicateRule(String),
+
+    #[error("Mapping rule not found: {0}")]
+    RuleNotFound(String),
+
+    #[error("Member error: {0}")]
+    Member(#[from] MemberError),
+
+    #[error("Activity error: {0}")]
+    Activity(#[from] ActivityError),
+}
+
+pub type GroupMappingResult<T> = Result<T, GroupMappingError>;
+
+// ============================================================================
+// Core Types
+// ============================================================================
+
+/// What an identity has to carry for a rule to apply
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GroupMatch {
+    /// Member of the named group (case-insensitive)
+    Group(String),
+
+    /// Member of any group whose name starts with the prefix (case-insensitive)
+    GroupPrefix(String),
+
+    /// Claim or SAML attribute with the given value
+    Claim { name: String, value: String },
+}
+
+impl GroupMatch {
+    /// Whether the identity satisfies this match
+    pub fn matches(&self, identity: &IdentityClaims) -> bool {
+        match self {
+            GroupMatch::Group(group) => identity.groups.iter().any(|g| g.eq_ignore_ascii_case(group)),
+            GroupMatch::GroupPrefix(prefix) => {
+                let prefix = prefix.to_ascii_lowercase();
+                identity
+                    .groups
+                    .iter()
+                    .any(|g| g.to_ascii_lowercase().starts_with(&prefix))
+            }
+            GroupMatch::Claim { name, value } => identity
+                .claims
+                .get(name)
+                .is_some_and(|values| values.contains(value)),
+        }
+    }
+}
+
+/// Rule granting a workspace membership to identities matching a group or claim
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupMappingRule {
+    /// Rule ID
+    pub id: String,
+
+    /// What the identity must carry
+    pub source: GroupMatch,
+
+    /// Workspace to grant membership of
+    pub workspace_id: String,
+
+    /// Role to grant
+    pub role: MemberRole,
+
+    /// Team/department to place the member in
+    pub team: Option<String>,
+
+    /// Rules with higher priority pick the team when several match
+    pub priority: u32,
+
+    /// Whether the rule is applied
+    pub enabled: bool,
+}
+
+impl GroupMappingRule {
+    /// Create an enabled rule with no team and priority 0
+    pub fn new(id: &str, source: GroupMatch, workspace_id: &str, role: MemberRole) -> Self {
+        Self {
+            id: id.to_string(),
+            source,
+            workspace_id: workspace_id.to_string(),
+            role,
+            team: None,
+            priority: 0,
+            enabled: true,
+        }
+    }
+
+    /// Place matching members in a team
+    pub fn with_team(mut self, team: &str) -> Self {
+        self.team = Some(team.to_string());
+        self
+    }
+
+    /// Set the priority
+    pub fn with_priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Identity as asserted by the identity provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentityClaims {
+    /// User ID (SAML subject / OIDC `sub` / SCIM `externalId`)
+    pub user_id: String,
+
+    /// Email address
+    pub email: String,
+
+    /// Group names
+    pub groups: Vec<String>,
+
+    /// All claims or attributes, flattened to string values
+    pub claims: HashMap<String, Vec<String>>,
+
+    /// Whether the account is active; a SCIM deprovision sends `false`
+    pub active: bool,
+}
+
+impl IdentityClaims {
+    /// Create an active identity with the given groups
+    pub fn new(user_id: &str, email: &str, groups: Vec<String>) -> Self {
+        Self {
+            user_id: user_id.to_string(),
+            email: email.to_string(),
+            groups,
+            claims: HashMap::new(),
+            active: true,
+        }
+    }
+
+    /// Identity from SAML assertion attributes, with groups taken from
+    /// `group_attribute`
+    pub fn from_saml(
+        subject: &str,
+        email: &str,
+        attributes: &HashMap<String, Vec<String>>,
+        group_attribute: &str,
+    ) -> Self {
+        Self {
+            groups: attributes.get(group_attribute).cloned().unwrap_or_default(),
+            claims: attributes.clone(),
+            ..Self::new(subject, email, Vec::new())
+        }
+    }
+
+    /// Identity from OIDC ID token claims, with groups taken from
+    /// `groups_claim`
+    ///
+    /// Strings, numbers and booleans become single values and arrays become
+    /// one value per scalar element; objects are skipped.
+    pub fn from_oidc(
+        subject: &str,
+        email: &str,
+        claims: &HashMap<String, serde_json::Value>,
+        groups_claim: &str,
+    ) -> Self {
+        fn scalar(value: &serde_json::Value) -> Option<String> {
+            match value {
+                serde_json::Value::String(s) => Some(s.clone()),
+                serde_json::Value::Number(n) => Some(n.to_string()),
+                serde_json::Value::Bool(b) => Some(b.to_string()),
+                _ => None,
+            }
+        }
+
+        let claims: HashMap<String, Vec<String>> = claims
+            .iter()
+            .filter_map(|(name, value)| {
+                let values = match value {
+                    serde_json::Value::Array(items) => items.iter().filter_map(scalar).collect(),
+                    other => vec![scalar(other)?],
+                };
+                Some((name.clone(), values))
+            })
+            .collect();
+        Self {
+            groups: claims.get(groups_claim).cloned().unwrap_or_default(),
+            claims,
+            ..Self::new(subject, email, Vec::new())
+        }
+    }
+}
+
+/// What triggered a sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SyncTrigger {
+    /// User signed in through SSO
+    Login,
+
+    /// Identity provider pushed a SCIM update
+    Scim,
+}
+
+/// Membership a set of rules grants in one workspace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MappedMembership {
+    /// Most privileged role among the matching rules
+    pub role: MemberRole,
+
+    /// Team of the highest-priority matching rule that sets one
+    pub team: Option<String>,
+
+    /// IDs of the matching rules
+    pub rule_ids: Vec<String>,
+}
+
+/// Change a sync makes to one membership
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MembershipChange {
+    /// New membership
+    Added {
+        workspace_id: String,
+        role: MemberRole,
+        team: Option<String>,
+    },
+
+    /// Role of a managed membership changed
+    RoleChanged {
+        workspace_id: String,
+        from: MemberRole,
+        to: MemberRole,
+    },
+
+    /// Team of a managed membership changed
+    TeamChanged {
+        workspace_id: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+
+    /// Managed membership no longer granted by any rule
+    Removed { workspace_id: String, role: MemberRole },
+}
+
+impl MembershipChange {
+    /// Workspace the change applies to
+    pub fn workspace_id(&self) -> &str {
+        match self {
+            MembershipChange::Added { workspace_id, .. }
+            | MembershipChange::RoleChanged { workspace_id, .. }
+            | MembershipChange::TeamChanged { workspace_id, .. }
+            | MembershipChange::Removed { workspace_id, .. } => workspace_id,
+        }
+    }
+}
+
+/// Changes a sync makes, or would make in a dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncPlan {
+    /// User being synced
+    pub user_id: String,
+
+    /// What triggered the sync
+    pub trigger: SyncTrigger,
+
+    /// Membership granted per workspace
+    pub memberships: BTreeMap<String, MappedMembership>,
+
+    /// Changes, in workspace order
+    pub changes: Vec<MembershipChange>,
+}
+
+impl SyncPlan {
+    /// Whether the sync changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+// ============================================================================
+// Group Mapper
+// ============================================================================
+
+/// Applies group mapping rules to workspace memberships
+#[derive(Debug)]
+pub struct GroupMapper {
+    rules: Vec<GroupMappingRule>,
+    revoke_unmatched: bool,
+}
+
+impl GroupMapper {
+    /// Create a mapper with no rules that revokes memberships no rule grants
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            revoke_unmatched: true,
+        }
+    }
+
+    /// Whether managed memberships no rule grants any more are removed
+    pub fn set_revoke_unmatched(&mut self, revoke: bool) {
+        self.revoke_unmatched = revoke;
+    }
+
+    /// Add a mapping rule
+    ///
+    /// Rules can't grant ownership: owners are appointed by hand, and a sync
+    /// removing one could leave a workspace without an owner.
+    pub fn add_rule(&mut self, rule: GroupMappingRule) -> GroupMappingResult<()> {
+        if rule.role == MemberRole::Owner {
+            return Err(GroupMappingError::InvalidRule(format!(
+                "{}: the owner role can't be granted by mapping",
+                rule.id
+            )));
+        }
+        if rule.workspace_id.is_empty() {
+            return Err(GroupMappingError::InvalidRule(format!("{}: no workspace", rule.id)));
+        }
+        if self.rules.iter().any(|r| r.id == rule.id) {
+            return Err(GroupMappingError::DuplicateRule(rule.id));
+        }
+        self.rules.push(rule);
+        Ok(())
+    }
+
+    /// Remove a mapping rule
+    pub fn remove_rule(&mut self, rule_id: &str) -> GroupMappingResult<GroupMappingRule> {
+        let index = self
+            .rules
+            .iter()
+            .position(|r| r.id == rule_id)
+            .ok_or_else(|| GroupMappingError::RuleNotFound(rule_id.to_string()))?;
+        Ok(self.rules.remove(index))
+    }
+
+    /// Enable or disable a mapping rule
+    pub fn set_rule_enabled(&mut self, rule_id: &str, enabled: bool) -> GroupMappingResult<()> {
+        let rule = self
+            .rules
+            .iter_mut()
+            .find(|r| r.id == rule_id)
+            .ok_or_else(|| GroupMappingError::RuleNotFound(rule_id.to_string()))?;
+        rule.enabled = enabled;
+        Ok(())
+    }
+
+    /// All mapping rules
+    pub fn rules(&self) -> &[GroupMappingRule] {
+        &self.rules
+    }
+
+    /// Memberships the rules grant an identity, by workspace
+    ///
+    /// An inactive identity is granted nothing.
+    pub fn resolve(&self, identity: &IdentityClaims) -> BTreeMap<String, MappedMembership> {
+        let mut matching: Vec<&GroupMappingRule> = self
+            .rules
+            .iter()
+            .filter(|r| r.enabled && identity.active && r.source.matches(identity))
+            .collect();
+        matching.sort_by(|a, b| b.priority.cmp(&a.priority));
+
+        let mut memberships = BTreeMap::new();
+        for rule in matching {
+            let membership = memberships
+                .entry(rule.workspace_id.clone())
+                .or_insert_with(|| MappedMembership {
+                    role: rule.role,
+                    team: None,
+                    rule_ids: Vec::new(),
+                });
+            if rule.role.hierarchy_level() > membership.role.hierarchy_level() {
+                membership.role = rule.role;
+            }
+            if membership.team.is_none() {
+                membership.team = rule.team.clone();
+            }
+            membership.rule_ids.push(rule.id.clone());
+        }
+        memberships
+    }
+
+    /// Dry run: the changes syncing the identity would make, without making them
+    pub fn preview(&self, identity: &IdentityClaims, members: &MemberManager, trigger: SyncTrigger) -> SyncPlan {
+        let memberships = self.resolve(identity);
+        let mut changes = Vec::new();
+
+        for (workspace_id, mapped) in &memberships {
+            match members.get_member_by_user_id(workspace_id, &identity.user_id) {
+                Err(_) => changes.push(MembershipChange::Added {
+                    workspace_id: workspace_id.clone(),
+                    role: mapped.role,
+                    team: mapped.team.clone(),
+                }),
+                Ok(member) if is_directory_managed(member) => {
+                    if member.role != mapped.role {
+                        changes.push(MembershipChange::RoleChanged {
+                            workspace_id: workspace_id.clone(),
+                            from: member.role,
+                            to: mapped.role,
+                        });
+                    }
+                    if member.team != mapped.team {
+                        changes.push(MembershipChange::TeamChanged {
+                            workspace_id: workspace_id.clone(),
+                            from: member.team.clone(),
+                            to: mapped.team.clone(),
+                        });
+                    }
+                }
+                Ok(_) => {}
+            }
+        }
+
+        if self.revoke_unmatched {
+            let mut revoked: Vec<&Member> = members
+                .list_user_memberships(&identity.user_id)
+                .into_iter()
+                .filter(|m| is_directory_managed(m) && !memberships.contains_key(&m.workspace_id))
+                .collect();
+            revoked.sort_by(|a, b| a.workspace_id.cmp(&b.workspace_id));
+            changes.extend(revoked.into_iter().map(|m| MembershipChange::Removed {
+                workspace_id: m.workspace_id.clone(),
+                role: m.role,
+            }));
+        }
+
+        SyncPlan {
+            user_id: identity.user_id.clone(),
+            trigger,
+            memberships,
+            changes,
+        }
+    }
+
+    /// Sync the identity's memberships, logging each change to the audit trail
+    pub fn apply(
+        &self,
+        identity: &IdentityClaims,
+        trigger: SyncTrigger,
+        members: &mut MemberManager,
+        activity: &mut ActivityManager,
+    ) -> GroupMappingResult<SyncPlan> {
+        let plan = self.preview(identity, members, trigger);
+        let via = match trigger {
+            SyncTrigger::Login => "login",
+            SyncTrigger::Scim => "SCIM sync",
+        };
+
+        for change in &plan.changes {
+            let workspace_id = change.workspace_id();
+            let (activity_type, description, member_id, before, after) = match change {
+                MembershipChange::Added { role, team, .. } => {
+                    let member = members.add_member(
+                        workspace_id.to_string(),
+                        identity.user_id.clone(),
+                        identity.email.clone(),
+                        *role,
+                    )?;
+                    let managed = members.member_mut(&member.id)?;
+                    managed.team = team.clone();
+                    managed
+                        .metadata
+                        .insert(DIRECTORY_MANAGED_KEY.to_string(), "true".to_string());
+                    (
+                        ActivityType::MemberJoined,
+                        format!("Added {} as {:?} from identity provider groups at {}", identity.email, role, via),
+                        member.id,
+                        HashMap::new(),
+                        membership_fields(*role, team.as_deref()),
+                    )
+                }
+                MembershipChange::RoleChanged { from, to, .. } => {
+                    let member = members.get_member_by_user_id(workspace_id, &identity.user_id)?.id.clone();
+                    members.member_mut(&member)?.role = *to;
+                    (
+                        ActivityType::MemberRoleChanged,
+                        format!("Changed {} from {:?} to {:?} at {}", identity.email, from, to, via),
+                        member,
+                        HashMap::from([("role".to_string(), format!("{:?}", from))]),
+                        HashMap::from([("role".to_string(), format!("{:?}", to))]),
+                    )
+                }
+                MembershipChange::TeamChanged { from, to, .. } => {
+                    let member = members.get_member_by_user_id(workspace_id, &identity.user_id)?.id.clone();
+                    members.member_mut(&member)?.team = to.clone();
+                    (
+                        ActivityType::MemberRoleChanged,
+                        format!("Moved {} to team {} at {}", identity.email, to.as_deref().unwrap_or("(none)"), via),
+                        member,
+                        HashMap::from([("team".to_string(), from.clone().unwrap_or_default())]),
+                        HashMap::from([("team".to_string(), to.clone().unwrap_or_default())]),
+                    )
+                }
+                MembershipChange::Removed { role, .. } => {
+                    let member = members.get_member_by_user_id(workspace_id, &identity.user_id)?.id.clone();
+                    let team = members.member_mut(&member)?.team.clone();
+                    members.remove_member(workspace_id, &member)?;
+                    (
+                        ActivityType::MemberRemoved,
+                        format!("Removed {} as no identity provider group grants access, at {}", identity.email, via),
+                        member,
+                        membership_fields(*role, team.as_deref()),
+                        HashMap::new(),
+                    )
+                }
+            };
+
+            let logged = activity.log_activity_with_changes(
+                workspace_id.to_string(),
+                SYNC_ACTOR.to_string(),
+                activity_type,
+                description,
+                Some(member_id),
+                before,
+                after,
+            )?;
+            activity.audit_activity(&logged.id, &[SYNC_AUDIT_TAG])?;
+        }
+
+        Ok(plan)
+    }
+}
+
+impl Default for GroupMapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether a membership was created by group mapping
+pub fn is_directory_managed(member: &Member) -> bool {
+    member.metadata.get(DIRECTORY_MANAGED_KEY).is_some_and(|v| v == "true")
+}
+
+fn membership_fields(role: MemberRole, team: Option<&str>) -> HashMap<String, String> {
+    HashMap::from([
+        ("role".to_string(), format!("{:?}", role)),
+        ("team".to_string(), team.unwrap_or_default().to_string()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapper() -> GroupMapper {
+        let mut mapper = GroupMapper::new();
+        let eng = GroupMappingRule::new("eng", GroupMatch::Group("Engineering".into()), "ws1", MemberRole::Developer)
+            .with_team("Platform");
+        let leads = GroupMappingRule::new("leads", GroupMatch::GroupPrefix("eng-lead".into()), "ws1", MemberRole::Manager)
+            .with_priority(10);
+        mapper.add_rule(eng).unwrap();
+        mapper.add_rule(leads).unwrap();
+        mapper
+            .add_rule(GroupMappingRule::new(
+                "contractors",
+                GroupMatch::Claim { name: "employeeType".into(), value: "contractor".into() },
+                "ws2",
+                MemberRole::Viewer,
+            ))
+            .unwrap();
+        mapper
+    }
+
+    #[test]
+    fn test_resolve_picks_highest_role() {
+        let mapper = mapper();
+        let identity = IdentityClaims::new("u1", "u1@example.com", vec!["engineering".into(), "ENG-LEADS-EU".into()]);
+
+        let resolved = mapper.resolve(&identity);
+        let ws1 = &resolved["ws1"];
+        assert_eq!(ws1.role, MemberRole::Manager);
+        assert_eq!(ws1.team.as_deref(), Some("Platform"));
+        assert_eq!(ws1.rule_ids, vec!["leads".to_string(), "eng".to_string()]);
+        assert!(!resolved.contains_key("ws2"));
+
+        let claims = HashMap::from([
+            ("groups".to_string(), serde_json::json!(["Engineering"])),
+            ("employeeType".to_string(), serde_json::json!("contractor")),
+        ]);
+        let identity = IdentityClaims::from_oidc("u2", "u2@example.com", &claims, "groups");
+        assert_eq!(mapper.resolve(&identity).keys().collect::<Vec<_>>(), vec!["ws1", "ws2"]);
+
+        let mut mapper = mapper;
+        let owners = GroupMappingRule::new("owners", GroupMatch::Group("Admins".into()), "ws1", MemberRole::Owner);
+        assert!(matches!(mapper.add_rule(owners), Err(GroupMappingError::InvalidRule(_))));
+    }
+
+    #[test]
+    fn test_preview_does_not_change_memberships() {
+        let mapper = mapper();
+        let members = MemberManager::new();
+        let identity = IdentityClaims::new("u1", "u1@example.com", vec!["Engineering".into()]);
+
+        let plan = mapper.preview(&identity, &members, SyncTrigger::Login);
+        assert_eq!(
+            plan.changes,
+            vec![MembershipChange::Added {
+                workspace_id: "ws1".into(),
+                role: MemberRole::Developer,
+                team: Some("Platform".into()),
+            }]
+        );
+        assert!(!members.is_member("ws1", "u1"));
+    }
+
+    #[test]
+    fn test_sync_adds_updates_and_revokes_with_audit() {
+        let mapper = mapper();
+        let mut members = MemberManager::new();
+        let mut activity = ActivityManager::new();
+
+        // Manual membership in ws2 is left alone throughout
+        members
+            .add_member("ws2".into(), "u1".into(), "u1@example.com".into(), MemberRole::Admin)
+            .unwrap();
+
+        let mut identity = IdentityClaims::new("u1", "u1@example.com", vec!["Engineering".into()]);
+        identity.claims.insert("employeeType".into(), vec!["contractor".into()]);
+        let plan = mapper.apply(&identity, SyncTrigger::Login, &mut members, &mut activity).unwrap();
+        assert_eq!(plan.changes.len(), 1);
+        let member = members.get_member_by_user_id("ws1", "u1").unwrap();
+        assert!(is_directory_managed(member));
+        assert_eq!(member.team.as_deref(), Some("Platform"));
+
+        // Promotion through a new group, then a second sync is a no-op
+        identity.groups.push("eng-leads".into());
+        let plan = mapper.apply(&identity, SyncTrigger::Scim, &mut members, &mut activity).unwrap();
+        assert_eq!(
+            plan.changes,
+            vec![MembershipChange::RoleChanged {
+                workspace_id: "ws1".into(),
+                from: MemberRole::Developer,
+                to: MemberRole::Manager,
+            }]
+        );
+        assert!(mapper.apply(&identity, SyncTrigger::Login, &mut members, &mut activity).unwrap().is_empty());
+
+        // Deprovisioning removes the managed membership only
+        identity.active = false;
+        let plan = mapper.apply(&identity, SyncTrigger::Scim, &mut members, &mut activity).unwrap();
+        assert_eq!(
+            plan.changes,
+            vec![MembershipChange::Removed {
+                workspace_id: "ws1".into(),
+                role: MemberRole::Manager,
+            }]
+        );
+        assert!(!members.is_member("ws1", "u1"));
+        assert_eq!(members.get_member_by_user_id("ws2", "u1").unwrap().role, MemberRole::Admin);
+
+        let trail = activity.get_audit_trail("ws1", 10);
+        assert_eq!(trail.len(), 3);
+        assert!(trail.iter().all(|e| e.compliance_tags.iter().any(|t| t == SYNC_AUDIT_TAG)));
+        assert!(trail.iter().all(|e| e.changes.is_some()));
+    }
+}
@@ -751,6 +751,21 @@ impl MemberManager {
             .ok_or_else(|| MemberError::NotFound(user_id.to_string()))
     }
 
+    /// List a user's memberships across all workspaces
+    pub fn list_user_memberships(&self, user_id: &str) -> Vec<&Member> {
+        self.members
+            .values()
+            .filter(|m| m.user_id == user_id)
+            .collect()
+    }
+
+    /// Get mutable member by ID, bypassing permission checks
+    pub(crate) fn member_mut(&mut self, member_id: &str) -> MemberResult<&mut Member> {
+        self.members
+            .get_mut(member_id)
+            .ok_or_else(|| MemberError::NotFound(member_id.to_string()))
+    }
+
     /// Get member activity
     pub fn get_member_activity(&self, member_id: &str) -> Option<&MemberActivity> {
         self.activities.get(member_id)
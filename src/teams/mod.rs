@@ -7,6 +7,10 @@
 //! - **Issue Assignments**: Advanced assignment workflows with load balancing
 //! - **Comments & Discussions**: Rich collaboration features with mentions and threading
 //! - **Activity Tracking**: Real-time activity streams and audit trails
+//! - **Group Mapping**: SAML/OIDC group and claim rules that sync workspace
+//!   memberships at login and on SCIM pushes
+//! - **Team Usage**: Per-team storage and compute attribution, team quotas,
+//!   usage dashboards and chargeback CSV export
 //!
 //! # Architecture
 //!
@@ -17,6 +21,8 @@
 //! 3. **Assignment Layer**: Manages issue assignment and workload distribution
 //! 4. **Comment Layer**: Facilitates team communication and discussions
 //! 5. **Activity Layer**: Tracks and reports all team activities
+//! 6. **Mapping Layer**: Derives memberships from identity provider groups
+//! 7. **Usage Layer**: Attributes tenant resource usage to teams
 //!
 //! # Example Usage
 //!
@@ -66,6 +72,8 @@ pub mod members;
 pub mod assignments;
 pub mod comments;
 pub mod activity;
+pub mod mapping;
+pub mod usage;
 
 // ============================================================================
 // Re-exports
@@ -100,6 +108,16 @@ pub use activity::{
     AuditEntry,
 };
 
+pub use mapping::{
+    GroupMapper, GroupMappingRule, GroupMatch, GroupMappingError, GroupMappingResult,
+    IdentityClaims, MappedMembership, MembershipChange, SyncPlan, SyncTrigger,
+};
+
+pub use usage::{
+    TeamUsageLedger, TeamUsageEntry, TeamUsageError, TeamUsageResult, TeamResource,
+    TeamQuota, TeamQuotaStatus, TeamUsageSummary,
+};
+
 // ============================================================================
 // Team Collaboration System Facade
 // ============================================================================
@@ -123,6 +141,12 @@ pub struct TeamCollaborationSystem {
 
     /// Activity tracking
     pub activity_manager: ActivityManager,
+
+    /// Identity provider group mapping
+    pub group_mapper: GroupMapper,
+
+    /// Per-team usage and quotas
+    pub usage_ledger: TeamUsageLedger,
 }
 
 impl TeamCollaborationSystem {
@@ -134,6 +158,8 @@ impl TeamCollaborationSystem {
             assignment_manager: AssignmentManager::new(),
             comment_manager: CommentManager::new(),
             activity_manager: ActivityManager::new(),
+            group_mapper: GroupMapper::new(),
+            usage_ledger: TeamUsageLedger::new(),
         }
     }
 
@@ -183,6 +209,90 @@ impl TeamCollaborationSystem {
         Ok(workspace)
     }
 
+    /// Add a group mapping rule for an existing workspace
+    pub fn add_group_mapping(&mut self, rule: GroupMappingRule) -> Result<(), Box<dyn std::error::Error>> {
+        self.workspace_manager.get_workspace(&rule.workspace_id)?;
+        self.group_mapper.add_rule(rule)?;
+        Ok(())
+    }
+
+    /// Sync a user's memberships from their identity provider groups at SSO login
+    pub fn sync_identity_on_login(&mut self, identity: &IdentityClaims) -> GroupMappingResult<SyncPlan> {
+        self.group_mapper.apply(
+            identity,
+            SyncTrigger::Login,
+            &mut self.member_manager,
+            &mut self.activity_manager,
+        )
+    }
+
+    /// Sync a user's memberships from a SCIM user or group push
+    pub fn sync_identity_from_scim(&mut self, identity: &IdentityClaims) -> GroupMappingResult<SyncPlan> {
+        self.group_mapper.apply(
+            identity,
+            SyncTrigger::Scim,
+            &mut self.member_manager,
+            &mut self.activity_manager,
+        )
+    }
+
+    /// Dry run: the membership changes a sync would make
+    pub fn preview_identity_sync(&self, identity: &IdentityClaims, trigger: SyncTrigger) -> SyncPlan {
+        self.group_mapper.preview(identity, &self.member_manager, trigger)
+    }
+
+    /// Record storage or compute usage against an existing team
+    pub fn record_team_usage(
+        &mut self,
+        workspace_id: &str,
+        resource: TeamResource,
+        amount: i64,
+        resource_id: Option<String>,
+        recorded_by: Option<String>,
+    ) -> Result<TeamUsageEntry, Box<dyn std::error::Error>> {
+        self.workspace_manager.get_workspace(workspace_id)?;
+        Ok(self.usage_ledger.record(workspace_id, resource, amount, resource_id, recorded_by)?)
+    }
+
+    /// Set a team's soft and hard limits for a resource
+    pub fn set_team_quota(
+        &mut self,
+        workspace_id: &str,
+        resource: TeamResource,
+        soft_limit: i64,
+        hard_limit: i64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.workspace_manager.get_workspace(workspace_id)?;
+        self.usage_ledger.set_quota(workspace_id, resource, soft_limit, hard_limit)?;
+        Ok(())
+    }
+
+    /// Usage dashboard rows for a period, labelled with team names
+    pub fn team_usage_dashboard(
+        &self,
+        period_start: chrono::DateTime<chrono::Utc>,
+        period_end: chrono::DateTime<chrono::Utc>,
+    ) -> Vec<TeamUsageSummary> {
+        let mut summaries = self.usage_ledger.summaries(period_start, period_end);
+        for summary in &mut summaries {
+            summary.team_name = self
+                .workspace_manager
+                .get_workspace(&summary.team_id)
+                .ok()
+                .map(|w| w.name.clone());
+        }
+        summaries
+    }
+
+    /// Chargeback CSV of team usage for a period
+    pub fn export_team_usage_csv(
+        &self,
+        period_start: chrono::DateTime<chrono::Utc>,
+        period_end: chrono::DateTime<chrono::Utc>,
+    ) -> String {
+        usage::usage_csv(&self.team_usage_dashboard(period_start, period_end))
+    }
+
     /// Get comprehensive team statistics
     pub fn get_team_stats(&self, workspace_id: &str) -> Result<TeamStats, Box<dyn std::error::Error>> {
         let workspace = self.workspace_manager.get_workspace(workspace_id)?;
@@ -263,4 +373,59 @@ mod tests {
         assert_eq!(workspace.name, "Test Team");
         assert_eq!(workspace.slug, "test-team");
     }
+
+    #[test]
+    fn test_identity_sync_on_login() {
+        let mut system = TeamCollaborationSystem::new();
+        let workspace = system.create_team(
+            "Design".to_string(),
+            "design".to_string(),
+            "owner123".to_string(),
+            vec![],
+        ).unwrap();
+
+        let rule = GroupMappingRule::new(
+            "designers",
+            GroupMatch::Group("Designers".to_string()),
+            &workspace.id,
+            MemberRole::Designer,
+        );
+        system.add_group_mapping(rule).unwrap();
+        let missing = GroupMappingRule::new("x", GroupMatch::Group("X".to_string()), "nowhere", MemberRole::Viewer);
+        assert!(system.add_group_mapping(missing).is_err());
+
+        let identity = IdentityClaims::new("user9", "user9@example.com", vec!["Designers".to_string()]);
+        assert_eq!(system.preview_identity_sync(&identity, SyncTrigger::Login).changes.len(), 1);
+        assert!(!system.member_manager.is_member(&workspace.id, "user9"));
+
+        system.sync_identity_on_login(&identity).unwrap();
+        let member = system.member_manager.get_member_by_user_id(&workspace.id, "user9").unwrap();
+        assert_eq!(member.role, MemberRole::Designer);
+        assert_eq!(system.activity_manager.get_audit_trail(&workspace.id, 10).len(), 1);
+    }
+
+    #[test]
+    fn test_team_usage_export() {
+        let mut system = TeamCollaborationSystem::new();
+        let workspace = system.create_team(
+            "Structures".to_string(),
+            "structures".to_string(),
+            "owner123".to_string(),
+            vec![],
+        ).unwrap();
+
+        system.set_team_quota(&workspace.id, TeamResource::Compute, 600, 3_600).unwrap();
+        system.record_team_usage(&workspace.id, TeamResource::Compute, 1_200, None, None).unwrap();
+        assert!(system.record_team_usage(&workspace.id, TeamResource::Compute, 3_000, None, None).is_err());
+        assert!(system.record_team_usage("nowhere", TeamResource::Storage, 10, None, None).is_err());
+
+        let start = usage::period_start(chrono::Utc::now());
+        let end = chrono::Utc::now() + chrono::Duration::minutes(1);
+        let dashboard = system.team_usage_dashboard(start, end);
+        assert_eq!(dashboard.len(), 1);
+        assert_eq!(dashboard[0].team_name.as_deref(), Some("Structures"));
+
+        let csv = system.export_team_usage_csv(start, end);
+        assert!(csv.lines().nth(1).unwrap().ends_with(",Structures,Compute,seconds,1200,3600,33.33,100.00"));
+    }
 }
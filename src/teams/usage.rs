@@ -0,0 +1,454 @@
+//! Team Usage and Quota Module
+//!
+//! Attributes tenant resource usage to the teams (workspaces) that caused it:
+//! - Storage and compute usage recorded per team
+//! - Team-level soft and hard quotas on top of the tenant's plan
+//! - Dashboard summaries with quota consumption and tenant share
+//! - CSV export for internal chargeback
+//!
+//! Storage is a running balance: uploads add bytes and deletions subtract
+//! them, so a team's storage is everything it has recorded up to a point in
+//! time. Compute is consumed per billing period, so it is summed over the
+//! period and its quota resets each calendar month.
+
+use crate::saas::usage::UsageMetric;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+use uuid::Uuid;
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+#[derive(Error, Debug)]
+pub enum TeamUsageError {
+    #[error("Team {team_id} would exceed its {resource:?} quota: {requested} requested, {remaining} remaining")]
+    QuotaExceeded {
+        team_id: String,
+        resource: TeamResource,
+        requested: i64,
+        remaining: i64,
+    },
+
+    #[error("Invalid quota: {0}")]
+    InvalidQuota(String),
+
+    #[error("Invalid usage amount: {0}")]
+    InvalidAmount(i64),
+}
+
+pub type TeamUsageResult<T> = Result<T, TeamUsageError>;
+
+// ============================================================================
+// Core Types
+// ============================================================================
+
+/// Resource a team consumes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TeamResource {
+    /// Stored bytes (files, documents, projects)
+    Storage,
+
+    /// Compute seconds (renders, exports, analysis jobs)
+    Compute,
+}
+
+impl TeamResource {
+    pub fn display_name(&self) -> &str {
+        match self {
+            TeamResource::Storage => "Storage",
+            TeamResource::Compute => "Compute",
+        }
+    }
+
+    pub fn unit(&self) -> &str {
+        match self {
+            TeamResource::Storage => "bytes",
+            TeamResource::Compute => "seconds",
+        }
+    }
+
+    /// Tenant-level metric the usage is metered under
+    pub fn metric(&self) -> UsageMetric {
+        match self {
+            TeamResource::Storage => UsageMetric::Storage,
+            TeamResource::Compute => UsageMetric::Compute,
+        }
+    }
+
+    /// Whether usage accumulates across periods rather than resetting
+    pub fn is_balance(&self) -> bool {
+        matches!(self, TeamResource::Storage)
+    }
+}
+
+/// One usage record attributed to a team
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamUsageEntry {
+    pub id: String,
+    pub team_id: String,
+    pub resource: TeamResource,
+
+    /// Amount in the resource's unit; negative for storage that was freed
+    pub amount: i64,
+
+    /// File, job or document the usage came from
+    pub resource_id: Option<String>,
+
+    pub recorded_by: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Team-level limits for one resource
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamQuota {
+    pub team_id: String,
+    pub resource: TeamResource,
+
+    /// Usage above which the team is warned
+    pub soft_limit: i64,
+
+    /// Usage the team may not exceed
+    pub hard_limit: i64,
+}
+
+/// Team's standing against its quota
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamQuotaStatus {
+    pub team_id: String,
+    pub resource: TeamResource,
+    pub used: i64,
+    pub soft_limit: Option<i64>,
+    pub hard_limit: Option<i64>,
+
+    /// Amount still available under the hard limit
+    pub remaining: Option<i64>,
+
+    pub over_soft_limit: bool,
+    pub over_hard_limit: bool,
+}
+
+/// Dashboard row: one team's usage of one resource over a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamUsageSummary {
+    pub team_id: String,
+    pub team_name: Option<String>,
+    pub resource: TeamResource,
+
+    /// Storage held at the end of the period, or compute used during it
+    pub used: i64,
+
+    pub record_count: usize,
+    pub hard_limit: Option<i64>,
+
+    /// Percentage of the hard limit used
+    pub quota_percent: Option<f64>,
+
+    /// Percentage of the tenant's total for the resource, the basis for
+    /// splitting the tenant's bill in chargeback
+    pub tenant_share_percent: f64,
+}
+
+// ============================================================================
+// Team Usage Ledger
+// ============================================================================
+
+/// Records team usage and enforces team quotas
+#[derive(Debug, Default)]
+pub struct TeamUsageLedger {
+    entries: Vec<TeamUsageEntry>,
+    quotas: HashMap<(String, TeamResource), TeamQuota>,
+}
+
+impl TeamUsageLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a team's limits for a resource, replacing any existing ones
+    pub fn set_quota(
+        &mut self,
+        team_id: &str,
+        resource: TeamResource,
+        soft_limit: i64,
+        hard_limit: i64,
+    ) -> TeamUsageResult<()> {
+        if soft_limit < 0 || hard_limit <= 0 || soft_limit > hard_limit {
+            return Err(TeamUsageError::InvalidQuota(format!(
+                "soft limit {} and hard limit {} for {}",
+                soft_limit, hard_limit, team_id
+            )));
+        }
+
+        self.quotas.insert(
+            (team_id.to_string(), resource),
+            TeamQuota {
+                team_id: team_id.to_string(),
+                resource,
+                soft_limit,
+                hard_limit,
+            },
+        );
+        Ok(())
+    }
+
+    /// Remove a team's limits for a resource
+    pub fn remove_quota(&mut self, team_id: &str, resource: TeamResource) -> Option<TeamQuota> {
+        self.quotas.remove(&(team_id.to_string(), resource))
+    }
+
+    pub fn get_quota(&self, team_id: &str, resource: TeamResource) -> Option<&TeamQuota> {
+        self.quotas.get(&(team_id.to_string(), resource))
+    }
+
+    /// Record usage for a team
+    ///
+    /// Usage that would take the team past its hard limit is rejected and
+    /// nothing is recorded. Negative amounts are only valid for storage.
+    pub fn record(
+        &mut self,
+        team_id: &str,
+        resource: TeamResource,
+        amount: i64,
+        resource_id: Option<String>,
+        recorded_by: Option<String>,
+    ) -> TeamUsageResult<TeamUsageEntry> {
+        if amount == 0 || (amount < 0 && !resource.is_balance()) {
+            return Err(TeamUsageError::InvalidAmount(amount));
+        }
+
+        let now = Utc::now();
+        if amount > 0 {
+            let status = self.status_at(team_id, resource, now);
+            if let Some(remaining) = status.remaining {
+                if amount > remaining {
+                    return Err(TeamUsageError::QuotaExceeded {
+                        team_id: team_id.to_string(),
+                        resource,
+                        requested: amount,
+                        remaining,
+                    });
+                }
+            }
+        }
+
+        let entry = TeamUsageEntry {
+            id: Uuid::new_v4().to_string(),
+            team_id: team_id.to_string(),
+            resource,
+            amount,
+            resource_id,
+            recorded_by,
+            recorded_at: now,
+        };
+        self.entries.push(entry.clone());
+        Ok(entry)
+    }
+
+    /// Whether `amount` more usage fits under the team's hard limit
+    pub fn can_consume(&self, team_id: &str, resource: TeamResource, amount: i64) -> bool {
+        self.quota_status(team_id, resource)
+            .remaining
+            .is_none_or(|remaining| amount <= remaining)
+    }
+
+    /// Team's current standing against its quota
+    pub fn quota_status(&self, team_id: &str, resource: TeamResource) -> TeamQuotaStatus {
+        self.status_at(team_id, resource, Utc::now())
+    }
+
+    fn status_at(&self, team_id: &str, resource: TeamResource, at: DateTime<Utc>) -> TeamQuotaStatus {
+        let start = if resource.is_balance() { None } else { Some(period_start(at)) };
+        let used = self.total(team_id, resource, start, None);
+        let quota = self.get_quota(team_id, resource);
+
+        TeamQuotaStatus {
+            team_id: team_id.to_string(),
+            resource,
+            used,
+            soft_limit: quota.map(|q| q.soft_limit),
+            hard_limit: quota.map(|q| q.hard_limit),
+            remaining: quota.map(|q| (q.hard_limit - used).max(0)),
+            over_soft_limit: quota.is_some_and(|q| used > q.soft_limit),
+            over_hard_limit: quota.is_some_and(|q| used > q.hard_limit),
+        }
+    }
+
+    /// Teams at or above their soft limit for any resource
+    pub fn teams_over_soft_limit(&self) -> Vec<TeamQuotaStatus> {
+        let mut statuses: Vec<TeamQuotaStatus> = self
+            .quotas
+            .values()
+            .map(|q| self.quota_status(&q.team_id, q.resource))
+            .filter(|s| s.over_soft_limit)
+            .collect();
+        statuses.sort_by(|a, b| (&a.team_id, a.resource).cmp(&(&b.team_id, b.resource)));
+        statuses
+    }
+
+    /// Usage entries for a team, oldest first
+    pub fn entries_for_team(&self, team_id: &str) -> Vec<&TeamUsageEntry> {
+        self.entries.iter().filter(|e| e.team_id == team_id).collect()
+    }
+
+    fn total(
+        &self,
+        team_id: &str,
+        resource: TeamResource,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> i64 {
+        self.entries
+            .iter()
+            .filter(|e| e.team_id == team_id && e.resource == resource)
+            .filter(|e| from.is_none_or(|from| e.recorded_at >= from))
+            .filter(|e| to.is_none_or(|to| e.recorded_at < to))
+            .map(|e| e.amount)
+            .sum()
+    }
+
+    /// Per-team usage over `[period_start, period_end)`, ordered by team
+    /// then resource
+    pub fn summaries(&self, period_start: DateTime<Utc>, period_end: DateTime<Utc>) -> Vec<TeamUsageSummary> {
+        let mut rows: BTreeMap<(String, TeamResource), (i64, usize)> = BTreeMap::new();
+        for entry in self.entries.iter().filter(|e| e.recorded_at < period_end) {
+            let in_period = entry.recorded_at >= period_start;
+            if !in_period && !entry.resource.is_balance() {
+                continue;
+            }
+            let row = rows.entry((entry.team_id.clone(), entry.resource)).or_default();
+            row.0 += entry.amount;
+            if in_period {
+                row.1 += 1;
+            }
+        }
+
+        let mut tenant_totals: HashMap<TeamResource, i64> = HashMap::new();
+        for ((_, resource), (used, _)) in &rows {
+            *tenant_totals.entry(*resource).or_default() += (*used).max(0);
+        }
+
+        rows.into_iter()
+            .map(|((team_id, resource), (used, record_count))| {
+                let hard_limit = self.get_quota(&team_id, resource).map(|q| q.hard_limit);
+                let tenant_total = tenant_totals[&resource];
+                TeamUsageSummary {
+                    quota_percent: hard_limit.map(|limit| used as f64 / limit as f64 * 100.0),
+                    tenant_share_percent: if tenant_total > 0 {
+                        used.max(0) as f64 / tenant_total as f64 * 100.0
+                    } else {
+                        0.0
+                    },
+                    team_id,
+                    team_name: None,
+                    resource,
+                    used,
+                    record_count,
+                    hard_limit,
+                }
+            })
+            .collect()
+    }
+
+    /// Summaries for the current calendar month
+    pub fn current_period_summaries(&self) -> Vec<TeamUsageSummary> {
+        let now = Utc::now();
+        self.summaries(period_start(now), now + chrono::Duration::seconds(1))
+    }
+}
+
+/// Start of the calendar month containing `at`, the period compute quotas
+/// reset on
+pub fn period_start(at: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(at.year(), at.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(at)
+}
+
+/// Chargeback CSV (RFC 4180) of dashboard rows
+pub fn usage_csv(summaries: &[TeamUsageSummary]) -> String {
+    let mut out = String::from(
+        "team_id,team_name,resource,unit,used,hard_limit,quota_percent,tenant_share_percent\r\n",
+    );
+    for s in summaries {
+        let fields = [
+            s.team_id.clone(),
+            s.team_name.clone().unwrap_or_default(),
+            s.resource.display_name().to_string(),
+            s.resource.unit().to_string(),
+            s.used.to_string(),
+            s.hard_limit.map(|l| l.to_string()).unwrap_or_default(),
+            s.quota_percent.map(|p| format!("{:.2}", p)).unwrap_or_default(),
+            format!("{:.2}", s.tenant_share_percent),
+        ];
+        let fields: Vec<String> = fields.iter().map(|f| quote(f)).collect();
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hard_limit_rejects_usage() {
+        let mut ledger = TeamUsageLedger::new();
+        ledger.set_quota("ws-1", TeamResource::Storage, 800, 1_000).unwrap();
+        assert!(ledger.set_quota("ws-1", TeamResource::Compute, 10, 5).is_err());
+
+        ledger.record("ws-1", TeamResource::Storage, 900, Some("plan.dwg".to_string()), None).unwrap();
+        assert!(ledger.quota_status("ws-1", TeamResource::Storage).over_soft_limit);
+        assert!(!ledger.can_consume("ws-1", TeamResource::Storage, 200));
+
+        let err = ledger.record("ws-1", TeamResource::Storage, 200, None, None).unwrap_err();
+        assert!(matches!(err, TeamUsageError::QuotaExceeded { remaining: 100, .. }));
+        assert_eq!(ledger.entries_for_team("ws-1").len(), 1);
+
+        // Freeing storage makes room again
+        ledger.record("ws-1", TeamResource::Storage, -500, None, None).unwrap();
+        ledger.record("ws-1", TeamResource::Storage, 200, None, None).unwrap();
+        assert_eq!(ledger.quota_status("ws-1", TeamResource::Storage).used, 600);
+        assert!(ledger.teams_over_soft_limit().is_empty());
+        assert!(ledger.record("ws-1", TeamResource::Compute, -5, None, None).is_err());
+    }
+
+    #[test]
+    fn test_summaries_and_csv() {
+        let mut ledger = TeamUsageLedger::new();
+        ledger.set_quota("ws-a", TeamResource::Compute, 50, 100).unwrap();
+        ledger.record("ws-a", TeamResource::Compute, 30, None, None).unwrap();
+        ledger.record("ws-b", TeamResource::Compute, 90, None, None).unwrap();
+        ledger.record("ws-b", TeamResource::Storage, 4_096, None, None).unwrap();
+
+        let mut summaries = ledger.current_period_summaries();
+        assert_eq!(summaries.len(), 3);
+        let a = &summaries[0];
+        assert_eq!((a.team_id.as_str(), a.resource, a.used), ("ws-a", TeamResource::Compute, 30));
+        assert!((a.quota_percent.unwrap() - 30.0).abs() < 1e-9);
+        assert!((a.tenant_share_percent - 25.0).abs() < 1e-9);
+        assert!((summaries[1].tenant_share_percent - 100.0).abs() < 1e-9);
+
+        summaries[0].team_name = Some("Design, East".to_string());
+        let csv = usage_csv(&summaries);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1], "ws-a,\"Design, East\",Compute,seconds,30,100,30.00,25.00");
+        assert_eq!(lines[2], "ws-b,,Storage,bytes,4096,,,100.00");
+    }
+}
@@ -2,8 +2,17 @@
 // Provides grid display and snapping functionality
 
 use super::Point2;
+use crate::io::document::DocumentSettings;
 use std::f64::consts::PI;
 
+pub use crate::io::document::{GridType, Isoplane};
+
+/// Distance between parallel isometric grid lines, per unit of spacing
+const ISO_ROW: f64 = 0.866_025_403_784_438_6; // cos 30°
+
+/// Upper bound on grid lines per viewport axis before spacing is coarsened
+const MAX_LINES_ACROSS: f64 = 200.0;
+
 /// Grid display and snapping system
 pub struct Grid {
     /// Grid settings
@@ -26,6 +35,29 @@ impl Grid {
         }
     }
 
+    /// Current minor (snap) spacing, after adaptive scaling
+    pub fn spacing(&self) -> f64 {
+        if self.settings.adaptive {
+            self.adaptive_spacing
+        } else {
+            self.settings.spacing
+        }
+    }
+
+    /// Current major line spacing
+    pub fn major_spacing(&self) -> f64 {
+        self.spacing() * self.major_every() as f64
+    }
+
+    /// Minor steps between major lines
+    fn major_every(&self) -> i64 {
+        if self.settings.subdivisions > 0 {
+            self.settings.subdivisions as i64
+        } else {
+            10
+        }
+    }
+
     /// Snap point to grid
     pub fn snap_point(&self, point: Point2) -> Point2 {
         if !self.snap_enabled {
@@ -35,15 +67,12 @@ impl Grid {
         match self.settings.grid_type {
             GridType::Rectangular => self.snap_rectangular(point),
             GridType::Polar => self.snap_polar(point),
+            GridType::Isometric => self.snap_isometric(point),
         }
     }
 
     fn snap_rectangular(&self, point: Point2) -> Point2 {
-        let spacing = if self.settings.adaptive {
-            self.adaptive_spacing
-        } else {
-            self.settings.spacing
-        };
+        let spacing = self.spacing();
 
         let x = (point.x / spacing).round() * spacing;
         let y = (point.y / spacing).round() * spacing;
@@ -57,15 +86,11 @@ impl Grid {
         let radius = (point.x * point.x + point.y * point.y).sqrt();
 
         // Snap radius to grid spacing
-        let spacing = if self.settings.adaptive {
-            self.adaptive_spacing
-        } else {
-            self.settings.spacing
-        };
+        let spacing = self.spacing();
         let snapped_radius = (radius / spacing).round() * spacing;
 
         // Snap angle to angular divisions
-        let angle_spacing = 2.0 * PI / self.settings.polar_divisions as f64;
+        let angle_spacing = 2.0 * PI / self.settings.polar_divisions.max(1) as f64;
         let snapped_angle = (angle / angle_spacing).round() * angle_spacing;
 
         Point2::new(
@@ -74,38 +99,64 @@ impl Grid {
         )
     }
 
+    fn snap_isometric(&self, point: Point2) -> Point2 {
+        // Lattice spanned by the 30° and 150° axes; (u, v) are its coordinates
+        let spacing = self.spacing();
+        let u = point.x / (2.0 * ISO_ROW * spacing) + point.y / spacing;
+        let v = point.y / spacing - point.x / (2.0 * ISO_ROW * spacing);
+
+        // The lattice is skewed, so rounding each coordinate alone can miss
+        // the nearest node; compare the four surrounding ones
+        let mut best = point;
+        let mut best_distance = f64::INFINITY;
+        for u in [u.floor(), u.ceil()] {
+            for v in [v.floor(), v.ceil()] {
+                let node = Self::iso_node(u, v, spacing);
+                let distance = node.distance_to(&point);
+                if distance < best_distance {
+                    best_distance = distance;
+                    best = node;
+                }
+            }
+        }
+        best
+    }
+
+    fn iso_node(u: f64, v: f64, spacing: f64) -> Point2 {
+        Point2::new((u - v) * ISO_ROW * spacing, (u + v) * 0.5 * spacing)
+    }
+
     /// Update adaptive grid spacing based on zoom level
+    ///
+    /// Spacing steps by the subdivision factor from the base spacing, so the
+    /// major lines at one zoom level become the minor lines at the next.
     pub fn update_adaptive_spacing(&mut self, pixel_size: f64, viewport_width: u32) {
-        if !self.settings.adaptive {
-            self.adaptive_spacing = self.settings.spacing;
+        let base_spacing = self.settings.spacing;
+        let usable = |v: f64| v.is_finite() && v > 0.0;
+        if !self.settings.adaptive || !usable(pixel_size) || !usable(base_spacing) {
+            self.adaptive_spacing = base_spacing;
             return;
         }
 
-        // Calculate world units visible in viewport
+        // Smallest spacing that stays readable and keeps the line count bounded
         let visible_width = pixel_size * viewport_width as f64;
+        let min_spacing = (self.settings.min_pixel_spacing * pixel_size)
+            .max(visible_width / MAX_LINES_ACROSS);
 
-        // Find appropriate grid spacing (powers of 10, 2, or 5)
-        let base_spacing = self.settings.spacing;
-        let mut spacing = base_spacing;
-
-        // Aim for 10-20 grid lines across viewport
-        let target_lines = 15.0;
-        let desired_spacing = visible_width / target_lines;
-
-        // Find nearest "nice" number
-        let magnitude = 10_f64.powf(desired_spacing.log10().floor());
-        let normalized = desired_spacing / magnitude;
-
-        spacing = if normalized < 1.5 {
-            magnitude
-        } else if normalized < 3.5 {
-            magnitude * 2.0
-        } else if normalized < 7.5 {
-            magnitude * 5.0
+        let factor = if self.settings.subdivisions >= 2 {
+            self.settings.subdivisions as f64
         } else {
-            magnitude * 10.0
+            10.0
         };
 
+        let mut spacing = base_spacing;
+        while spacing < min_spacing {
+            spacing *= factor;
+        }
+        while spacing / factor >= min_spacing {
+            spacing /= factor;
+        }
+
         self.adaptive_spacing = spacing;
     }
 
@@ -114,75 +165,53 @@ impl Grid {
         match self.settings.grid_type {
             GridType::Rectangular => self.get_rectangular_lines(viewport),
             GridType::Polar => self.get_polar_lines(viewport),
+            GridType::Isometric => self.get_isometric_lines(viewport),
         }
     }
 
     fn get_rectangular_lines(&self, viewport: GridViewport) -> GridLines {
-        let spacing = if self.settings.adaptive {
-            self.adaptive_spacing
-        } else {
-            self.settings.spacing
-        };
+        let spacing = self.spacing();
+        let every = self.major_every();
 
         let mut major_lines = Vec::new();
         let mut minor_lines = Vec::new();
 
-        // Calculate grid bounds
-        let min_x = (viewport.min.x / spacing).floor() * spacing;
-        let max_x = (viewport.max.x / spacing).ceil() * spacing;
-        let min_y = (viewport.min.y / spacing).floor() * spacing;
-        let max_y = (viewport.max.y / spacing).ceil() * spacing;
+        // Lines are indexed from the origin so major lines stay put while panning
+        let (first_x, last_x) = index_range(viewport.min.x, viewport.max.x, spacing);
+        let (first_y, last_y) = index_range(viewport.min.y, viewport.max.y, spacing);
+        let (min_x, max_x) = (first_x as f64 * spacing, last_x as f64 * spacing);
+        let (min_y, max_y) = (first_y as f64 * spacing, last_y as f64 * spacing);
 
         // Generate vertical lines
-        let mut x = min_x;
-        let mut count = 0;
-        while x <= max_x {
-            let is_major = if self.settings.subdivisions > 0 {
-                count % self.settings.subdivisions == 0
-            } else {
-                (x / spacing).abs() % 10.0 < 0.01
-            };
-
+        for i in first_x..=last_x {
+            let x = i as f64 * spacing;
             let line = GridLine {
                 start: Point2::new(x, min_y),
                 end: Point2::new(x, max_y),
-                is_axis: x.abs() < 1e-10,
+                is_axis: i == 0,
             };
 
-            if is_major {
+            if i.rem_euclid(every) == 0 {
                 major_lines.push(line);
             } else {
                 minor_lines.push(line);
             }
-
-            x += spacing;
-            count += 1;
         }
 
         // Generate horizontal lines
-        let mut y = min_y;
-        count = 0;
-        while y <= max_y {
-            let is_major = if self.settings.subdivisions > 0 {
-                count % self.settings.subdivisions == 0
-            } else {
-                (y / spacing).abs() % 10.0 < 0.01
-            };
-
+        for i in first_y..=last_y {
+            let y = i as f64 * spacing;
             let line = GridLine {
                 start: Point2::new(min_x, y),
                 end: Point2::new(max_x, y),
-                is_axis: y.abs() < 1e-10,
+                is_axis: i == 0,
             };
 
-            if is_major {
+            if i.rem_euclid(every) == 0 {
                 major_lines.push(line);
             } else {
                 minor_lines.push(line);
             }
-
-            y += spacing;
-            count += 1;
         }
 
         GridLines {
@@ -193,11 +222,8 @@ impl Grid {
     }
 
     fn get_polar_lines(&self, viewport: GridViewport) -> GridLines {
-        let spacing = if self.settings.adaptive {
-            self.adaptive_spacing
-        } else {
-            self.settings.spacing
-        };
+        let spacing = self.spacing();
+        let every = self.major_every();
 
         let mut major_lines = Vec::new();
         let mut minor_lines = Vec::new();
@@ -214,7 +240,7 @@ impl Grid {
         let mut radius = spacing;
         let mut count = 1;
         while radius <= max_radius {
-            let is_major = count % self.settings.subdivisions == 0;
+            let is_major = count % every == 0;
 
             // Create circle as line segments
             let segments = 64;
@@ -243,8 +269,9 @@ impl Grid {
         }
 
         // Generate radial lines
-        let angle_spacing = 2.0 * PI / self.settings.polar_divisions as f64;
-        for i in 0..self.settings.polar_divisions {
+        let divisions = self.settings.polar_divisions.max(1);
+        let angle_spacing = 2.0 * PI / divisions as f64;
+        for i in 0..divisions {
             let angle = i as f64 * angle_spacing;
             let end_x = max_radius * angle.cos();
             let end_y = max_radius * angle.sin();
@@ -252,7 +279,7 @@ impl Grid {
             let line = GridLine {
                 start: center,
                 end: Point2::new(end_x, end_y),
-                is_axis: i % (self.settings.polar_divisions / 4) == 0,
+                is_axis: i % (divisions / 4).max(1) == 0,
             };
 
             major_lines.push(line);
@@ -265,29 +292,87 @@ impl Grid {
         }
     }
 
+    fn get_isometric_lines(&self, viewport: GridViewport) -> GridLines {
+        let spacing = self.spacing();
+        let every = self.major_every();
+        let row = ISO_ROW * spacing;
+
+        let mut major_lines = Vec::new();
+        let mut minor_lines = Vec::new();
+
+        // Three families of parallel lines (90°, 30°, 150°), each a multiple
+        // of `row` apart along its normal
+        for direction in [90.0_f64, 30.0, 150.0] {
+            let (sin, cos) = direction.to_radians().sin_cos();
+            let dir = Point2::new(cos, sin);
+            let normal = Point2::new(-sin, cos);
+
+            let offsets = viewport.corners().map(|c| c.x * normal.x + c.y * normal.y);
+            let low = offsets.iter().cloned().fold(f64::INFINITY, f64::min);
+            let high = offsets.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+            let (first, last) = index_range(low, high, row);
+            for k in first..=last {
+                let offset = k as f64 * row;
+                let origin = Point2::new(normal.x * offset, normal.y * offset);
+                let Some((start, end)) = viewport.clip_line(origin, dir) else {
+                    continue;
+                };
+                if start.distance_to(&end) < 1e-9 {
+                    continue; // Grazes a corner
+                }
+
+                let line = GridLine {
+                    start,
+                    end,
+                    is_axis: k == 0,
+                };
+                if k.rem_euclid(every) == 0 {
+                    major_lines.push(line);
+                } else {
+                    minor_lines.push(line);
+                }
+            }
+        }
+
+        GridLines {
+            major: major_lines,
+            minor: minor_lines,
+            dots: Vec::new(),
+        }
+    }
+
     /// Get grid dots for dot-style grid display
     pub fn get_grid_dots(&self, viewport: GridViewport) -> Vec<Point2> {
-        let spacing = if self.settings.adaptive {
-            self.adaptive_spacing
-        } else {
-            self.settings.spacing
-        };
+        let spacing = self.spacing();
 
         let mut dots = Vec::new();
 
-        let min_x = (viewport.min.x / spacing).floor() * spacing;
-        let max_x = (viewport.max.x / spacing).ceil() * spacing;
-        let min_y = (viewport.min.y / spacing).floor() * spacing;
-        let max_y = (viewport.max.y / spacing).ceil() * spacing;
-
-        let mut y = min_y;
-        while y <= max_y {
-            let mut x = min_x;
-            while x <= max_x {
-                dots.push(Point2::new(x, y));
-                x += spacing;
+        if self.settings.grid_type == GridType::Isometric {
+            // Columns of nodes every `ISO_ROW * spacing`, alternate columns
+            // offset by half a spacing
+            let column = ISO_ROW * spacing;
+            let (first_i, last_i) = index_range(viewport.min.x, viewport.max.x, column);
+            for i in first_i..=last_i {
+                let shift = if i.rem_euclid(2) == 1 { 0.5 } else { 0.0 };
+                let (first_j, last_j) = index_range(
+                    viewport.min.y / spacing - shift,
+                    viewport.max.y / spacing - shift,
+                    1.0,
+                );
+                for j in first_j..=last_j {
+                    dots.push(Point2::new(i as f64 * column, (j as f64 + shift) * spacing));
+                }
+            }
+            return dots;
+        }
+
+        let (first_x, last_x) = index_range(viewport.min.x, viewport.max.x, spacing);
+        let (first_y, last_y) = index_range(viewport.min.y, viewport.max.y, spacing);
+        for j in first_y..=last_y {
+            for i in first_x..=last_x {
+                dots.push(Point2::new(i as f64 * spacing, j as f64 * spacing));
             }
-            y += spacing;
         }
 
         dots
@@ -313,6 +398,50 @@ impl Grid {
     pub fn set_subdivisions(&mut self, subdivisions: u32) {
         self.settings.subdivisions = subdivisions;
     }
+
+    /// Switch between rectangular, polar, and isometric grids
+    pub fn set_grid_type(&mut self, grid_type: GridType) {
+        self.settings.grid_type = grid_type;
+    }
+
+    /// Move to the next isoplane (Left → Top → Right) and return it
+    pub fn cycle_isoplane(&mut self) -> Isoplane {
+        self.settings.isoplane = self.settings.isoplane.next();
+        self.settings.isoplane
+    }
+
+    /// Take grid settings saved with a document
+    pub fn load_settings(&mut self, settings: &DocumentSettings) {
+        let grid = &settings.grid;
+        self.visible = grid.enabled;
+        self.snap_enabled = grid.snap;
+        self.settings.grid_type = grid.grid_type;
+        self.settings.isoplane = grid.isoplane;
+        self.settings.adaptive = grid.adaptive;
+        self.set_spacing(grid.minor_spacing);
+        self.settings.subdivisions = if grid.minor_spacing > 0.0 {
+            (grid.major_spacing / grid.minor_spacing).round().max(1.0) as u32
+        } else {
+            1
+        };
+    }
+
+    /// Save grid settings into a document
+    pub fn store_settings(&self, settings: &mut DocumentSettings) {
+        let grid = &mut settings.grid;
+        grid.enabled = self.visible;
+        grid.snap = self.snap_enabled;
+        grid.grid_type = self.settings.grid_type;
+        grid.isoplane = self.settings.isoplane;
+        grid.adaptive = self.settings.adaptive;
+        grid.minor_spacing = self.settings.spacing;
+        grid.major_spacing = self.settings.spacing * self.settings.subdivisions.max(1) as f64;
+    }
+}
+
+/// Indices of the grid lines `spacing` apart covering `min..=max`
+fn index_range(min: f64, max: f64, spacing: f64) -> (i64, i64) {
+    ((min / spacing).floor() as i64, (max / spacing).ceil() as i64)
 }
 
 impl Default for Grid {
@@ -324,14 +453,18 @@ impl Default for Grid {
 /// Grid configuration settings
 #[derive(Debug, Clone)]
 pub struct GridSettings {
-    /// Grid type (rectangular or polar)
+    /// Grid type (rectangular, polar, or isometric)
     pub grid_type: GridType,
+    /// Active isoplane for isometric grids
+    pub isoplane: Isoplane,
     /// Base grid spacing
     pub spacing: f64,
     /// Number of minor divisions between major lines
     pub subdivisions: u32,
     /// Enable adaptive grid (auto-adjust spacing based on zoom)
     pub adaptive: bool,
+    /// Adaptive grid: closest the minor lines may get, in pixels
+    pub min_pixel_spacing: f64,
     /// Polar grid: number of angular divisions
     pub polar_divisions: u32,
     /// Display style
@@ -356,9 +489,11 @@ impl Default for GridSettings {
     fn default() -> Self {
         Self {
             grid_type: GridType::Rectangular,
+            isoplane: Isoplane::Top,
             spacing: 1.0,
             subdivisions: 10,
             adaptive: true,
+            min_pixel_spacing: 8.0,
             polar_divisions: 12,
             display_style: GridDisplayStyle::Lines,
             major_color: [0.4, 0.4, 0.4, 0.8],
@@ -372,15 +507,6 @@ impl Default for GridSettings {
     }
 }
 
-/// Type of grid
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum GridType {
-    /// Standard rectangular grid
-    Rectangular,
-    /// Polar grid (concentric circles and radial lines)
-    Polar,
-}
-
 /// Grid display style
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GridDisplayStyle {
@@ -427,6 +553,45 @@ impl GridViewport {
             (self.min.y + self.max.y) / 2.0,
         )
     }
+
+    /// Corner points
+    pub fn corners(&self) -> [Point2; 4] {
+        [
+            self.min,
+            Point2::new(self.max.x, self.min.y),
+            self.max,
+            Point2::new(self.min.x, self.max.y),
+        ]
+    }
+
+    /// Clip the infinite line through `origin` along `dir` to the viewport
+    pub fn clip_line(&self, origin: Point2, dir: Point2) -> Option<(Point2, Point2)> {
+        let mut t0 = f64::NEG_INFINITY;
+        let mut t1 = f64::INFINITY;
+
+        for (o, d, min, max) in [
+            (origin.x, dir.x, self.min.x, self.max.x),
+            (origin.y, dir.y, self.min.y, self.max.y),
+        ] {
+            if d.abs() < 1e-12 {
+                if o < min || o > max {
+                    return None;
+                }
+                continue;
+            }
+            let (a, b) = ((min - o) / d, (max - o) / d);
+            t0 = t0.max(a.min(b));
+            t1 = t1.min(a.max(b));
+        }
+
+        if t0 > t1 {
+            return None;
+        }
+        Some((
+            Point2::new(origin.x + t0 * dir.x, origin.y + t0 * dir.y),
+            Point2::new(origin.x + t1 * dir.x, origin.y + t1 * dir.y),
+        ))
+    }
 }
 
 /// Grid lines for rendering
@@ -618,4 +783,124 @@ mod tests {
         assert!(lines.total_lines() > 0);
         assert!(!lines.major.is_empty());
     }
+
+    #[test]
+    fn test_major_lines_anchored_to_origin() {
+        let mut grid = Grid::new();
+        grid.settings.adaptive = false;
+        grid.set_subdivisions(5);
+
+        // Panned so the view does not start on a major line
+        let viewport = GridViewport::new(Point2::new(-3.0, -3.0), Point2::new(12.0, 12.0));
+        let lines = grid.get_grid_lines(viewport);
+
+        let mut major_x: Vec<f64> = lines
+            .major
+            .iter()
+            .filter(|l| (l.start.x - l.end.x).abs() < 1e-10)
+            .map(|l| l.start.x)
+            .collect();
+        major_x.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(major_x, vec![0.0, 5.0, 10.0]);
+        assert_eq!(lines.major.iter().filter(|l| l.is_axis).count(), 2);
+    }
+
+    #[test]
+    fn test_adaptive_spacing_steps_by_subdivisions() {
+        let mut grid = Grid::new();
+        grid.settings.spacing = 1.0;
+        grid.settings.subdivisions = 10;
+
+        // 0.5 units per pixel: 1-unit lines would be 2px apart
+        grid.update_adaptive_spacing(0.5, 800);
+        assert!((grid.spacing() - 10.0).abs() < 1e-10);
+        assert!((grid.major_spacing() - 100.0).abs() < 1e-10);
+
+        // Zoomed in: refine below the base spacing
+        grid.update_adaptive_spacing(0.001, 800);
+        assert!((grid.spacing() - 0.01).abs() < 1e-10);
+
+        grid.settings.adaptive = false;
+        grid.update_adaptive_spacing(0.5, 800);
+        assert!((grid.spacing() - 1.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_isometric_snap() {
+        let mut grid = Grid::new();
+        grid.settings.adaptive = false;
+        grid.set_spacing(10.0);
+        grid.set_grid_type(GridType::Isometric);
+
+        // Nodes sit on columns 10·cos30 apart, alternate columns offset by half
+        let snapped = grid.snap_point(Point2::new(9.0, 4.0));
+        assert!((snapped.x - 10.0 * ISO_ROW).abs() < 1e-10);
+        assert!((snapped.y - 5.0).abs() < 1e-10);
+
+        let snapped = grid.snap_point(Point2::new(0.5, 11.0));
+        assert!(snapped.x.abs() < 1e-10);
+        assert!((snapped.y - 10.0).abs() < 1e-10);
+
+        // Every dot is a node, and snapping a node leaves it in place
+        let viewport = GridViewport::new(Point2::new(-20.0, -20.0), Point2::new(20.0, 20.0));
+        let dots = grid.get_grid_dots(viewport);
+        assert!(!dots.is_empty());
+        for dot in dots {
+            assert!(grid.snap_point(dot).distance_to(&dot) < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_isometric_lines() {
+        let mut grid = Grid::new();
+        grid.settings.adaptive = false;
+        grid.set_grid_type(GridType::Isometric);
+
+        let viewport = GridViewport::new(Point2::new(-5.0, -5.0), Point2::new(5.0, 5.0));
+        let lines = grid.get_grid_lines(viewport);
+        assert!(!lines.minor.is_empty());
+
+        // Three axes through the origin, all lines inside the viewport
+        assert_eq!(lines.major.iter().filter(|l| l.is_axis).count(), 3);
+        for line in lines.major.iter().chain(&lines.minor) {
+            for p in [line.start, line.end] {
+                assert!(p.x >= -5.0 - 1e-9 && p.x <= 5.0 + 1e-9);
+                assert!(p.y >= -5.0 - 1e-9 && p.y <= 5.0 + 1e-9);
+            }
+            let angle = (line.end.y - line.start.y)
+                .atan2(line.end.x - line.start.x)
+                .to_degrees()
+                .rem_euclid(180.0);
+            assert!([30.0, 90.0, 150.0].iter().any(|a| (angle - a).abs() < 1e-6));
+        }
+    }
+
+    #[test]
+    fn test_isoplane_cycle_and_document_settings() {
+        let mut grid = Grid::new();
+        assert_eq!(grid.settings.isoplane, Isoplane::Top);
+        assert_eq!(grid.cycle_isoplane(), Isoplane::Right);
+        assert_eq!(grid.cycle_isoplane(), Isoplane::Left);
+        assert_eq!(grid.cycle_isoplane(), Isoplane::Top);
+
+        grid.set_grid_type(GridType::Isometric);
+        grid.cycle_isoplane();
+        grid.set_spacing(2.5);
+        grid.set_subdivisions(4);
+        grid.snap_enabled = false;
+
+        let mut settings = DocumentSettings::default();
+        grid.store_settings(&mut settings);
+        assert_eq!(settings.grid.grid_type, GridType::Isometric);
+        assert_eq!(settings.grid.isoplane, Isoplane::Right);
+        assert!((settings.grid.major_spacing - 10.0).abs() < 1e-10);
+
+        let mut restored = Grid::new();
+        restored.load_settings(&settings);
+        assert_eq!(restored.settings.grid_type, GridType::Isometric);
+        assert_eq!(restored.settings.isoplane, Isoplane::Right);
+        assert_eq!(restored.settings.subdivisions, 4);
+        assert!((restored.spacing() - 2.5).abs() < 1e-10);
+        assert!(!restored.snap_enabled);
+    }
 }
@@ -8,6 +8,8 @@ pub mod grid;
 pub mod transform;
 pub mod ortho;
 pub mod grip_edit;
+pub mod trim;
+pub mod spatial;
 
 // Re-export commonly used types
 pub use selection::{Selection, SelectionSet, SelectionMode, SelectionPreview};
@@ -17,6 +19,8 @@ pub use grid::{Grid, GridSettings, PolarGrid};
 pub use transform::{TransformMode, TransformGizmo, TransformOperation};
 pub use ortho::{OrthoMode, PolarTracking, SnapTracking};
 pub use grip_edit::{GripPoint, GripType, GripSet, GripEditor};
+pub use trim::{extend_to, trim_to, CurveEntity};
+pub use spatial::{EntitySource, IndexedEntities};
 
 use uuid::Uuid;
 
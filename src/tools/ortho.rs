@@ -1,7 +1,10 @@
 // Orthographic Mode and Polar Tracking - Complete implementation
 // Constrains input to orthogonal or polar angles
 
+use super::grid::{GridType, Isoplane};
 use super::{Point2, Vector2};
+use crate::io::document::DocumentSettings;
+use crate::io::parameters::{ParameterResult, ParameterValues};
 use std::f64::consts::PI;
 
 /// Orthographic mode controller
@@ -46,13 +49,13 @@ impl OrthoMode {
 
         let angle = dy.atan2(dx);
 
-        // Find nearest constraint angle
-        let constraint_angle = if self.polar_tracking.enabled {
-            self.find_nearest_polar_angle(angle)
-        } else {
-            self.find_nearest_ortho_angle(angle)
-        };
+        // Polar tracking takes over from ortho, and may also snap the distance
+        if self.polar_tracking.enabled {
+            self.current_angle = self.polar_tracking.tracking_angle(angle);
+            return self.polar_tracking.snap_point(from, to);
+        }
 
+        let constraint_angle = self.find_nearest_ortho_angle(angle);
         self.current_angle = Some(constraint_angle);
 
         // Project point onto constraint angle
@@ -71,11 +74,17 @@ impl OrthoMode {
             normalized -= 2.0 * PI;
         }
 
+        // Measure around the circle so 350° is near 0°
+        let angular_diff = |a: f64| {
+            let diff = (normalized - a).rem_euclid(2.0 * PI);
+            diff.min(2.0 * PI - diff)
+        };
+
         let mut nearest = self.angles[0];
-        let mut min_diff = (normalized - nearest).abs();
+        let mut min_diff = angular_diff(nearest);
 
         for &ortho_angle in &self.angles {
-            let diff = (normalized - ortho_angle).abs();
+            let diff = angular_diff(ortho_angle);
             if diff < min_diff {
                 min_diff = diff;
                 nearest = ortho_angle;
@@ -85,8 +94,37 @@ impl OrthoMode {
         nearest
     }
 
-    fn find_nearest_polar_angle(&self, angle: f64) -> f64 {
-        self.polar_tracking.find_nearest_angle(angle)
+    /// Constrain to an isoplane's axes, or back to horizontal/vertical
+    pub fn set_isoplane(&mut self, isoplane: Option<Isoplane>) {
+        self.angles = match isoplane {
+            Some(plane) => plane
+                .axes()
+                .iter()
+                .flat_map(|a| [*a, a + 180.0])
+                .map(|a| a.to_radians().rem_euclid(2.0 * PI))
+                .collect(),
+            None => vec![0.0, PI / 2.0, PI, 3.0 * PI / 2.0],
+        };
+        self.current_angle = None;
+    }
+
+    /// Take ortho and polar settings saved with a document
+    pub fn load_settings(&mut self, settings: &DocumentSettings) {
+        self.enabled = settings.snap.ortho;
+        self.polar_tracking.enabled = settings.snap.polar;
+        self.polar_tracking.set_increment(settings.snap.polar_increment);
+        self.polar_tracking.snap_distance = settings.snap.polar_distance.max(0.0);
+
+        let isometric = settings.grid.grid_type == GridType::Isometric;
+        self.set_isoplane(isometric.then_some(settings.grid.isoplane));
+    }
+
+    /// Save ortho and polar settings into a document
+    pub fn store_settings(&self, settings: &mut DocumentSettings) {
+        settings.snap.ortho = self.enabled;
+        settings.snap.polar = self.polar_tracking.enabled;
+        settings.snap.polar_increment = self.polar_tracking.increment;
+        settings.snap.polar_distance = self.polar_tracking.snap_distance;
     }
 
     /// Toggle ortho mode
@@ -138,6 +176,8 @@ pub struct PolarTracking {
     pub additional_angles: Vec<f64>,
     /// Tracking aperture (tolerance in radians)
     pub aperture: f64,
+    /// Polar snap: distance increment along tracking vectors (0 = off)
+    pub snap_distance: f64,
     /// Display settings
     pub settings: PolarTrackingSettings,
 }
@@ -149,6 +189,7 @@ impl PolarTracking {
             increment: 45.0, // 45 degrees
             additional_angles: Vec::new(),
             aperture: 5.0_f64.to_radians(), // 5 degrees tolerance
+            snap_distance: 0.0,
             settings: PolarTrackingSettings::default(),
         }
     }
@@ -159,6 +200,11 @@ impl PolarTracking {
             return angle;
         }
 
+        self.tracking_angle(angle).unwrap_or(angle)
+    }
+
+    /// Tracking angle within the aperture of `angle`, if any
+    pub fn tracking_angle(&self, angle: f64) -> Option<f64> {
         let increment_rad = self.increment.to_radians();
 
         // Normalize angle to 0..2π
@@ -177,19 +223,52 @@ impl PolarTracking {
         // Check if within aperture
         let diff = (normalized - snap_angle).abs();
         if diff <= self.aperture {
-            snap_angle
+            Some(snap_angle)
         } else {
             // Check additional angles
-            for &add_angle in &self.additional_angles {
-                let diff = (normalized - add_angle).abs();
-                if diff <= self.aperture {
-                    return add_angle;
-                }
-            }
-            angle // Return original if no snap
+            self.additional_angles
+                .iter()
+                .copied()
+                .find(|add_angle| (normalized - add_angle).abs() <= self.aperture)
         }
     }
 
+    /// Snap a point onto the nearest tracking vector from `from`
+    ///
+    /// With polar snap on, the distance along the vector is also rounded to
+    /// a multiple of `snap_distance`. Points outside every tracking aperture
+    /// are returned unchanged.
+    pub fn snap_point(&self, from: Point2, to: Point2) -> Point2 {
+        if !self.enabled {
+            return to;
+        }
+
+        let dx = to.x - from.x;
+        let dy = to.y - from.y;
+        let mut distance = (dx * dx + dy * dy).sqrt();
+        if distance < 1e-10 {
+            return to;
+        }
+
+        let Some(angle) = self.tracking_angle(dy.atan2(dx)) else {
+            return to;
+        };
+
+        if self.snap_distance > 0.0 {
+            distance = (distance / self.snap_distance).round() * self.snap_distance;
+        }
+
+        Point2::new(
+            from.x + distance * angle.cos(),
+            from.y + distance * angle.sin(),
+        )
+    }
+
+    /// Set polar snap distance (0 disables)
+    pub fn set_snap_distance(&mut self, distance: f64) {
+        self.snap_distance = if distance.is_finite() { distance.max(0.0) } else { 0.0 };
+    }
+
     /// Add tracking angle
     pub fn add_angle(&mut self, angle: f64) {
         self.additional_angles.push(angle);
@@ -458,6 +537,28 @@ impl DynamicInput {
         }
     }
 
+    /// Set a field from typed text such as `25.4mm*3 + 1in` or `sqrt(2)*10`
+    ///
+    /// The text is evaluated over the document's parameters and drawing units;
+    /// the field is left alone when it does not evaluate.
+    pub fn enter(
+        &mut self,
+        field: DynamicInputField,
+        text: &str,
+        values: &ParameterValues,
+    ) -> ParameterResult<()> {
+        let value = values.evaluate(text.trim())?;
+        let slot = match field {
+            DynamicInputField::X => &mut self.fields.x,
+            DynamicInputField::Y => &mut self.fields.y,
+            DynamicInputField::Z => &mut self.fields.z,
+            DynamicInputField::Distance => &mut self.fields.distance,
+            DynamicInputField::Angle => &mut self.fields.angle,
+        };
+        *slot = Some(value);
+        Ok(())
+    }
+
     /// Toggle dynamic input
     pub fn toggle(&mut self) {
         self.enabled = !self.enabled;
@@ -489,6 +590,17 @@ pub enum DynamicInputMode {
     Relative,
 }
 
+/// A dynamic input field values can be typed into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DynamicInputField {
+    X,
+    Y,
+    Z,
+    Distance,
+    /// Angle in degrees
+    Angle,
+}
+
 /// Dynamic input field values
 #[derive(Debug, Clone, Default)]
 pub struct DynamicInputFields {
@@ -566,6 +678,74 @@ mod tests {
         assert!(polar.enabled);
     }
 
+    #[test]
+    fn test_polar_snap_distance() {
+        let mut polar = PolarTracking::new();
+        polar.enabled = true;
+        polar.set_increment(30.0);
+        polar.set_snap_distance(5.0);
+
+        // 31° at length 12 tracks to 30° and snaps to length 10
+        let angle = 31.0_f64.to_radians();
+        let to = Point2::new(12.0 * angle.cos(), 12.0 * angle.sin());
+        let snapped = polar.snap_point(Point2::zero(), to);
+        let expected = 30.0_f64.to_radians();
+        assert!((snapped.x - 10.0 * expected.cos()).abs() < 1e-10);
+        assert!((snapped.y - 10.0 * expected.sin()).abs() < 1e-10);
+
+        // Outside the aperture nothing snaps
+        let angle = 15.0_f64.to_radians();
+        let to = Point2::new(12.0 * angle.cos(), 12.0 * angle.sin());
+        assert_eq!(polar.snap_point(Point2::zero(), to), to);
+    }
+
+    #[test]
+    fn test_isoplane_ortho() {
+        let mut ortho = OrthoMode::new();
+        ortho.enabled = true;
+        ortho.set_isoplane(Some(Isoplane::Top));
+
+        // Drawing near 340° follows the 150°/330° axis
+        let angle = 340.0_f64.to_radians();
+        let to = Point2::new(10.0 * angle.cos(), 10.0 * angle.sin());
+        ortho.constrain_point(Point2::zero(), to);
+        let current = ortho.current_angle().unwrap().to_degrees();
+        assert!((current - 330.0).abs() < 1e-9);
+
+        // Near-vertical is not an axis of the top plane
+        let constrained = ortho.constrain_point(Point2::zero(), Point2::new(1.0, 10.0));
+        assert!(constrained.x.abs() > 1.0);
+
+        ortho.set_isoplane(Some(Isoplane::Left));
+        let constrained = ortho.constrain_point(Point2::zero(), Point2::new(1.0, 10.0));
+        assert!(constrained.x.abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_ortho_document_settings() {
+        let mut ortho = OrthoMode::new();
+        ortho.polar_tracking.enabled = true;
+        ortho.polar_tracking.set_increment(15.0);
+        ortho.polar_tracking.set_snap_distance(2.5);
+
+        let mut settings = DocumentSettings::default();
+        settings.grid.grid_type = GridType::Isometric;
+        settings.grid.isoplane = Isoplane::Right;
+        ortho.store_settings(&mut settings);
+        assert!(settings.snap.polar);
+        assert_eq!(settings.snap.polar_increment, 15.0);
+
+        let mut restored = OrthoMode::new();
+        restored.load_settings(&settings);
+        assert!(restored.polar_tracking.enabled);
+        assert_eq!(restored.polar_tracking.snap_distance, 2.5);
+        assert_eq!(restored.angles.len(), 4);
+        assert!(restored
+            .angles
+            .iter()
+            .any(|a| (a.to_degrees() - 90.0).abs() < 1e-9));
+    }
+
     #[test]
     fn test_snap_tracking_alignment() {
         let mut tracking = SnapTracking::new();
@@ -628,4 +808,18 @@ mod tests {
         assert!(input.fields.angle.is_some());
         assert!(input.fields.x.is_none());
     }
+
+    #[test]
+    fn test_dynamic_input_expressions() {
+        let values = ParameterValues::empty(crate::io::units::Unit::Millimeters);
+        let mut input = DynamicInput::new();
+        input.enter(DynamicInputField::X, "25.4mm*3 + 1in", &values).unwrap();
+        input.enter(DynamicInputField::Angle, " 360/8 ", &values).unwrap();
+        assert!((input.fields.x.unwrap() - 101.6).abs() < 1e-9);
+        assert_eq!(input.fields.angle, Some(45.0));
+
+        // A bad expression leaves the field as it was
+        assert!(input.enter(DynamicInputField::X, "2 +", &values).is_err());
+        assert!((input.fields.x.unwrap() - 101.6).abs() < 1e-9);
+    }
 }
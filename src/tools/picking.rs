@@ -1,7 +1,10 @@
 // Object Picking System - Complete implementation
 // Handles picking entities in 2D and 3D views
 
+use super::spatial::{to_core_box, to_core_ray, EntitySource};
 use super::{EntityId, Point2, Point3, Ray3, Vector3, Entity, EntityType};
+use crate::geometry::raycast::RayCaster;
+use crate::geometry::spatial::ray_entry;
 
 /// Pick priority for different geometric features
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -70,6 +73,8 @@ pub enum PickedFeature {
     Intersection { entities: Vec<EntityId> },
     /// Interior point
     Interior,
+    /// Face of a 3D model, with surface parameters on tessellated surfaces
+    Face { index: usize, uv: Option<(f64, f64)> },
     /// Vertex of polyline/polygon
     Vertex { index: usize },
 }
@@ -170,10 +175,10 @@ impl Picker {
     }
 
     /// Pick in 2D view using screen coordinates
-    pub fn pick_2d(
+    pub fn pick_2d<E: EntitySource + ?Sized>(
         &self,
         screen_pos: Point2,
-        entities: &[Entity],
+        entities: &E,
         filter: &PickFilter,
         view_transform: &ViewTransform,
     ) -> Option<PickResult> {
@@ -194,10 +199,10 @@ impl Picker {
     }
 
     /// Pick all entities at 2D location
-    pub fn pick_2d_all(
+    pub fn pick_2d_all<E: EntitySource + ?Sized>(
         &self,
         screen_pos: Point2,
-        entities: &[Entity],
+        entities: &E,
         filter: &PickFilter,
         view_transform: &ViewTransform,
     ) -> Vec<PickResult> {
@@ -206,18 +211,12 @@ impl Picker {
 
         let mut results = Vec::new();
 
-        for entity in entities {
+        // Only entities whose bounds are near the pick point
+        for entity in entities.near(world_pos, tolerance) {
             if !filter.matches(entity) {
                 continue;
             }
 
-            // Check if entity bounds are near pick point
-            if let Some(bounds) = &entity.bounds {
-                if !bounds.intersects(&world_pos, tolerance) {
-                    continue;
-                }
-            }
-
             // Perform detailed picking based on entity type
             if let Some(pick) = self.pick_entity_2d(entity, world_pos, tolerance) {
                 results.push(pick);
@@ -273,13 +272,17 @@ impl Picker {
     }
 
     /// Pick in 3D view using ray casting
-    pub fn pick_3d(
+    ///
+    /// Entities with geometry in `scene` are hit exactly; the rest are
+    /// drawing entities lying flat at z = 0 and are hit on their bounds.
+    pub fn pick_3d<E: EntitySource + ?Sized>(
         &self,
         ray: Ray3,
-        entities: &[Entity],
+        entities: &E,
         filter: &PickFilter,
+        scene: &RayCaster<EntityId>,
     ) -> Option<PickResult> {
-        let mut results = self.pick_3d_all(ray, entities, filter);
+        let mut results = self.pick_3d_all(ray, entities, filter, scene);
 
         if results.is_empty() {
             return None;
@@ -296,11 +299,34 @@ impl Picker {
     }
 
     /// Pick all entities along ray
-    pub fn pick_3d_all(&self, ray: Ray3, entities: &[Entity], filter: &PickFilter) -> Vec<PickResult> {
-        let mut results = Vec::new();
+    pub fn pick_3d_all<E: EntitySource + ?Sized>(
+        &self,
+        ray: Ray3,
+        entities: &E,
+        filter: &PickFilter,
+        scene: &RayCaster<EntityId>,
+    ) -> Vec<PickResult> {
+        let core_ray = to_core_ray(&ray);
+
+        // Modelled geometry: the nearest hit on each entity's faces
+        let mut results: Vec<PickResult> = scene
+            .cast_all_by(&core_ray, self.max_distance, |id| {
+                entities.by_id(&id).is_some_and(|e| filter.matches(e))
+            })
+            .into_iter()
+            .map(|hit| {
+                PickResult::new(
+                    hit.key,
+                    Point3::new(hit.point.x, hit.point.y, hit.point.z),
+                    hit.t,
+                    PickPriority::Interior,
+                    PickedFeature::Face { index: hit.face, uv: hit.uv },
+                )
+            })
+            .collect();
 
-        for entity in entities {
-            if !filter.matches(entity) {
+        for entity in entities.along_ray(&ray, self.max_distance) {
+            if scene.contains(&entity.id) || !filter.matches(entity) {
                 continue;
             }
 
@@ -315,95 +341,54 @@ impl Picker {
     }
 
     fn pick_entity_3d(&self, entity: &Entity, ray: &Ray3) -> Option<PickResult> {
-        // Simplified ray-box intersection
-        // In real implementation would check actual geometry
-        if let Some(bounds) = &entity.bounds {
-            let box_center = Point3::new(
-                (bounds.min.x + bounds.max.x) / 2.0,
-                (bounds.min.y + bounds.max.y) / 2.0,
-                0.0,
-            );
-
-            // Simple distance to center check (not actual ray-box intersection)
-            let to_center = Vector3::new(
-                box_center.x - ray.origin.x,
-                box_center.y - ray.origin.y,
-                box_center.z - ray.origin.z,
-            );
-
-            let t = to_center.dot(&ray.direction);
-            if t < 0.0 {
-                return None;
-            }
-
-            let point = ray.point_at(t);
-            let distance = ray.origin.distance_to(&point);
-
-            // Check if ray passes near entity bounds
-            let half_width = (bounds.max.x - bounds.min.x) / 2.0;
-            let half_height = (bounds.max.y - bounds.min.y) / 2.0;
-            let max_size = half_width.max(half_height);
-
-            let offset = point.distance_to(&box_center);
-            if offset <= max_size {
-                return Some(PickResult::new(
-                    entity.id,
-                    point,
-                    distance,
-                    PickPriority::Interior,
-                    PickedFeature::Interior,
-                ));
-            }
-        }
-
-        None
+        // Flat drawing geometry: the ray against the plane patch its bounds cover
+        let bounds = entity.bounds?;
+        let t = ray_entry(&to_core_ray(ray), &to_core_box(&bounds))?;
+
+        Some(PickResult::new(
+            entity.id,
+            ray.point_at(t),
+            t,
+            PickPriority::Interior,
+            PickedFeature::Interior,
+        ))
     }
 
     /// Pick nearest point on entity to given point
-    pub fn pick_nearest(
+    pub fn pick_nearest<E: EntitySource + ?Sized>(
         &self,
         point: Point2,
-        entities: &[Entity],
+        entities: &E,
         filter: &PickFilter,
     ) -> Option<PickResult> {
-        let mut nearest: Option<PickResult> = None;
-        let mut min_distance = f64::INFINITY;
-
-        for entity in entities {
+        // Find nearest point on bounds (simplified to the center). Distance to
+        // the center is never below distance to the bounds, so the source can
+        // prune by bounds
+        let mut center_distance = |entity: &Entity| {
             if !filter.matches(entity) {
-                continue;
-            }
-
-            if let Some(bounds) = &entity.bounds {
-                // Find nearest point on bounds (simplified)
-                let center = Point2::new(
-                    (bounds.min.x + bounds.max.x) / 2.0,
-                    (bounds.min.y + bounds.max.y) / 2.0,
-                );
-
-                let distance = point.distance_to(&center);
-
-                if distance < min_distance {
-                    min_distance = distance;
-                    nearest = Some(PickResult::new(
-                        entity.id,
-                        Point3::new(center.x, center.y, 0.0),
-                        distance,
-                        PickPriority::Edge,
-                        PickedFeature::Edge { parameter: 0.5 },
-                    ));
-                }
+                return None;
             }
-        }
+            let bounds = entity.bounds?;
+            Some(point.distance_to(&bounds.min.midpoint(&bounds.max)))
+        };
 
-        nearest
+        let (entity, distance) = entities.nearest_by(point, &mut center_distance)?;
+        let bounds = entity.bounds?;
+        let center = bounds.min.midpoint(&bounds.max);
+        Some(PickResult::new(
+            entity.id,
+            Point3::new(center.x, center.y, 0.0),
+            distance,
+            PickPriority::Edge,
+            PickedFeature::Edge { parameter: 0.5 },
+        ))
     }
 
     /// Pick with snap support (integrates with snap system)
-    pub fn pick_with_snap(
+    pub fn pick_with_snap<E: EntitySource + ?Sized>(
         &self,
         point: Point2,
-        entities: &[Entity],
+        entities: &E,
         filter: &PickFilter,
         view_transform: &ViewTransform,
         snap_mode: u32,
@@ -420,10 +405,10 @@ impl Picker {
         self.pick_2d(point, entities, filter, view_transform)
     }
 
-    fn pick_snap(
+    fn pick_snap<E: EntitySource + ?Sized>(
         &self,
         point: Point2,
-        entities: &[Entity],
+        entities: &E,
         filter: &PickFilter,
         view_transform: &ViewTransform,
         snap_mode: u32,
@@ -433,7 +418,8 @@ impl Picker {
 
         let mut snap_results = Vec::new();
 
-        for entity in entities {
+        // Midpoint snaps reach twice the tolerance from the bounds center
+        for entity in entities.near(point, tolerance * 2.0) {
             if !filter.matches(entity) {
                 continue;
             }
@@ -639,6 +625,89 @@ mod tests {
         assert!((world.y - 0.0).abs() < 1e-10);
     }
 
+    #[test]
+    fn test_indexed_picking_matches_linear() {
+        use crate::tools::{BoundingBox2, IndexedEntities};
+
+        let entities: Vec<Entity> = (0..100)
+            .map(|i| {
+                let x = i as f64 * 4.0;
+                Entity::new(EntityType::Line)
+                    .with_bounds(BoundingBox2::from_points(Point2::new(x, 0.0), Point2::new(x + 2.0, 2.0)))
+            })
+            .collect();
+        let indexed = IndexedEntities::from_entities(entities.clone());
+        let picker = Picker::new();
+        let filter = PickFilter::new();
+
+        // Screen center over the middle of entity 10
+        let mut view = ViewTransform::new((800, 600));
+        view.center = Point2::new(41.0, 1.0);
+        let screen = Point2::new(400.0, 300.0);
+        let linear = picker.pick_2d(screen, &entities, &filter, &view).unwrap();
+        let fast = picker.pick_2d(screen, &indexed, &filter, &view).unwrap();
+        assert_eq!(linear.entity_id, entities[10].id);
+        assert_eq!(fast.entity_id, linear.entity_id);
+
+        let ray = Ray3::new(Point3::new(41.0, 1.0, 100.0), Vector3::new(0.0, 0.0, -1.0));
+        let scene = RayCaster::new();
+        let linear = picker.pick_3d(ray, &entities, &filter, &scene).unwrap();
+        let fast = picker.pick_3d(ray, &indexed, &filter, &scene).unwrap();
+        assert_eq!(linear.entity_id, entities[10].id);
+        assert_eq!(fast.entity_id, linear.entity_id);
+        assert!((fast.distance - 100.0).abs() < 1e-10);
+
+        let point = Point2::new(47.5, 5.0);
+        let linear = picker.pick_nearest(point, &entities, &filter).unwrap();
+        let fast = picker.pick_nearest(point, &indexed, &filter).unwrap();
+        assert_eq!(linear.entity_id, entities[12].id);
+        assert_eq!(fast.entity_id, linear.entity_id);
+    }
+
+    #[test]
+    fn test_pick_3d_hits_model_geometry() {
+        use crate::geometry::{Box3D, Sphere3D};
+        use crate::tools::BoundingBox2;
+        use nalgebra::Point3 as ModelPoint;
+
+        // A sphere over a box, both with plan bounds around the origin, and
+        // a flat drawing entity under them
+        let sphere = Entity::new(EntityType::Circle)
+            .with_bounds(BoundingBox2::from_points(Point2::new(-1.0, -1.0), Point2::new(1.0, 1.0)));
+        let mut locked = Entity::new(EntityType::Line)
+            .with_bounds(BoundingBox2::from_points(Point2::new(-2.0, -2.0), Point2::new(2.0, 2.0)));
+        locked.layer = "locked".to_string();
+        let flat = Entity::new(EntityType::Line)
+            .with_bounds(BoundingBox2::from_points(Point2::new(-3.0, -3.0), Point2::new(3.0, 3.0)));
+        let entities = vec![sphere.clone(), locked.clone(), flat.clone()];
+
+        let mut scene = RayCaster::new();
+        scene.insert(sphere.id, Sphere3D::new(ModelPoint::new(0.0, 0.0, 5.0), 1.0));
+        scene.insert(locked.id, Box3D::new(ModelPoint::new(0.0, 0.0, 2.0), 4.0, 4.0, 2.0));
+
+        let picker = Picker::new();
+        let filter = PickFilter::new();
+
+        // Past the sphere's silhouette but inside its bounds, the box is hit
+        let ray = Ray3::new(Point3::new(0.9, 0.9, 100.0), Vector3::new(0.0, 0.0, -1.0));
+        let pick = picker.pick_3d(ray, &entities, &filter, &scene).unwrap();
+        assert_eq!(pick.entity_id, locked.id);
+        assert!((pick.distance - 97.0).abs() < 1e-9);
+        assert!(matches!(pick.feature, PickedFeature::Face { index: 5, .. }));
+
+        let ray = Ray3::new(Point3::new(0.0, 0.0, 100.0), Vector3::new(0.0, 0.0, -1.0));
+        let pick = picker.pick_3d(ray, &entities, &filter, &scene).unwrap();
+        assert_eq!(pick.entity_id, sphere.id);
+        assert!((pick.point.z - 6.0).abs() < 1e-9);
+
+        // Filtered-out geometry is looked through, down to the flat entity
+        let filter = PickFilter::new().with_layers(vec!["0".to_string()]);
+        let all = picker.pick_3d_all(ray, &entities, &filter, &scene);
+        let ids: Vec<EntityId> = all.iter().map(|p| p.entity_id).collect();
+        assert_eq!(ids, vec![sphere.id, flat.id]);
+        assert!((all[1].distance - 100.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_pick_priority_order() {
         assert!(PickPriority::Endpoint < PickPriority::Midpoint);
@@ -2,6 +2,8 @@
 // Handles entity selection with multiple selection modes
 
 use super::{EntityId, Point2, BoundingBox2, Entity};
+use crate::io::document::Document;
+use crate::io::groups::{self, GroupResult};
 use std::collections::{HashSet, HashMap};
 use std::time::Instant;
 
@@ -417,6 +419,32 @@ impl Selection {
         self.set.save_previous();
         self.set.clear();
     }
+
+    /// Add picked entities, along with the groups they are in when
+    /// [`pick_groups`](SelectionSettings::pick_groups) is on
+    pub fn add_picked(&mut self, picked: &[EntityId], doc: &Document) {
+        let ids = if self.settings.pick_groups {
+            groups::expand_selection(doc, picked)
+        } else {
+            picked.to_vec()
+        };
+        self.set.add_many(&ids, self.mode);
+    }
+
+    /// Select a group, saved selection or saved query of the document by
+    /// name, replacing the selection; returns how many entities it holds
+    pub fn select_named(&mut self, doc: &Document, name: &str) -> GroupResult<usize> {
+        let ids = groups::resolve(doc, name)?;
+        self.set.save_previous();
+        self.set.clear();
+        self.set.add_many(&ids, SelectionMode::Add);
+        Ok(ids.len())
+    }
+
+    /// Save the selection in the document under a name, in selection order
+    pub fn save_named(&self, doc: &mut Document, name: &str) -> GroupResult<()> {
+        groups::save_selection(doc, name, &self.set.entities_ordered())
+    }
 }
 
 impl Default for Selection {
@@ -549,6 +577,8 @@ pub struct SelectionSettings {
     pub noun_verb: bool,
     /// Implied windowing (auto window on empty pick)
     pub implied_windowing: bool,
+    /// Picking a grouped entity selects its group
+    pub pick_groups: bool,
 }
 
 impl Default for SelectionSettings {
@@ -563,6 +593,7 @@ impl Default for SelectionSettings {
             select_locked: false,
             noun_verb: true,
             implied_windowing: true,
+            pick_groups: true,
         }
     }
 }
@@ -648,4 +679,32 @@ mod tests {
 
         assert!(preview.is_crossing_window());
     }
+
+    #[test]
+    fn test_pick_groups_and_named_selections() {
+        use crate::io::document::{Entity as DocEntity, GeometryType, Point, Vec3};
+
+        let mut doc = Document::new();
+        let ids: Vec<EntityId> = (0..3)
+            .map(|i| {
+                let point = Point { position: Vec3::new(i as f64, 0.0, 0.0) };
+                doc.add_entity(DocEntity::new(GeometryType::Point(point), "0".to_string()))
+            })
+            .collect();
+        groups::create_group(&mut doc, "Grid lines", &ids[0..2]).unwrap();
+
+        let mut selection = Selection::new();
+        selection.add_picked(&[ids[1]], &doc);
+        assert_eq!(selection.set.entities_ordered(), vec![ids[1], ids[0]]);
+
+        selection.save_named(&mut doc, "Phase 1").unwrap();
+        selection.settings.pick_groups = false;
+        selection.clear();
+        selection.add_picked(&[ids[1]], &doc);
+        assert_eq!(selection.set.len(), 1);
+
+        assert_eq!(selection.select_named(&doc, "Phase 1").unwrap(), 2);
+        assert!(selection.set.contains(&ids[0]));
+        assert!(selection.select_named(&doc, "Phase 2").is_err());
+    }
 }
@@ -1,6 +1,7 @@
 // Object Snap System - Complete implementation
 // Provides intelligent snapping to geometric features
 
+use super::spatial::EntitySource;
 use super::{EntityId, Point2, Point3, Entity, EntityType};
 
 /// Snap modes - bit flags for combining multiple snap types
@@ -217,10 +218,10 @@ impl ObjectSnap {
     }
 
     /// Find snap point near cursor position
-    pub fn find_snap(
+    pub fn find_snap<E: EntitySource + ?Sized>(
         &mut self,
         cursor: Point2,
-        entities: &[Entity],
+        entities: &E,
         pixel_size: f64,
     ) -> Option<SnapResult> {
         if self.mode.bits() == 0 {
@@ -236,20 +237,14 @@ impl ObjectSnap {
         self.find_closest_snap(cursor, tolerance)
     }
 
-    fn build_snap_cache(&mut self, cursor: Point2, entities: &[Entity], tolerance: f64) {
+    fn build_snap_cache<E: EntitySource + ?Sized>(&mut self, cursor: Point2, entities: &E, tolerance: f64) {
         self.snap_cache.clear();
 
         // Expand search area
         let search_tolerance = tolerance * 3.0;
 
-        for entity in entities {
-            // Quick bounds check
-            if let Some(bounds) = &entity.bounds {
-                if !bounds.intersects(&cursor, search_tolerance) {
-                    continue;
-                }
-            }
-
+        // Only entities whose bounds are near the cursor
+        for entity in entities.near(cursor, search_tolerance) {
             // Generate snap points based on entity type
             let mode = self.mode;
             self.generate_snap_points(entity, &mode);
@@ -391,10 +386,10 @@ impl ObjectSnap {
     }
 
     /// Find perpendicular snap from a reference point
-    pub fn find_perpendicular(
+    pub fn find_perpendicular<E: EntitySource + ?Sized>(
         &self,
         from_point: Point2,
-        entities: &[Entity],
+        entities: &E,
         tolerance: f64,
     ) -> Option<SnapResult> {
         if !self.mode.has(SnapMode::PERPENDICULAR) {
@@ -404,7 +399,8 @@ impl ObjectSnap {
         let mut best_result: Option<SnapResult> = None;
         let mut min_distance = f64::INFINITY;
 
-        for entity in entities {
+        // The perpendicular point lies within the bounds, so farther entities can't qualify
+        for entity in entities.near(from_point, tolerance) {
             if entity.entity_type != EntityType::Line {
                 continue;
             }
@@ -451,9 +447,9 @@ impl ObjectSnap {
     }
 
     /// Find intersection snap between two entities
-    pub fn find_intersection(
+    pub fn find_intersection<E: EntitySource + ?Sized>(
         &self,
-        entities: &[Entity],
+        entities: &E,
         tolerance: f64,
         cursor: Point2,
     ) -> Option<SnapResult> {
@@ -461,11 +457,13 @@ impl ObjectSnap {
             return None;
         }
 
-        // Check pairs of entities for intersections
-        for i in 0..entities.len() {
-            for j in (i + 1)..entities.len() {
+        // An intersection near the cursor lies in both entities' bounds, so
+        // only pairs of entities near the cursor need checking
+        let candidates = entities.near(cursor, tolerance);
+        for i in 0..candidates.len() {
+            for j in (i + 1)..candidates.len() {
                 if let Some(intersection) =
-                    self.calculate_intersection(&entities[i], &entities[j], tolerance, cursor)
+                    self.calculate_intersection(candidates[i], candidates[j], tolerance, cursor)
                 {
                     return Some(intersection);
                 }
@@ -516,10 +514,10 @@ impl ObjectSnap {
     }
 
     /// Find nearest point on entity
-    pub fn find_nearest(
+    pub fn find_nearest<E: EntitySource + ?Sized>(
         &self,
         cursor: Point2,
-        entities: &[Entity],
+        entities: &E,
         tolerance: f64,
     ) -> Option<SnapResult> {
         if !self.mode.has(SnapMode::NEAREST) {
@@ -529,12 +527,8 @@ impl ObjectSnap {
         let mut best_result: Option<SnapResult> = None;
         let mut min_distance = f64::INFINITY;
 
-        for entity in entities {
+        for entity in entities.near(cursor, tolerance) {
             if let Some(bounds) = &entity.bounds {
-                if !bounds.intersects(&cursor, tolerance) {
-                    continue;
-                }
-
                 // Find nearest point on entity (simplified to center)
                 let center = Point2::new(
                     (bounds.min.x + bounds.max.x) / 2.0,
@@ -654,6 +648,43 @@ mod tests {
         assert!(mode.has(SnapMode::CENTER));
     }
 
+    #[test]
+    fn test_intersection_checks_only_nearby_pairs() {
+        use crate::tools::{BoundingBox2, IndexedEntities};
+
+        let line = |x1: f64, y1: f64, x2: f64, y2: f64| {
+            Entity::new(EntityType::Line)
+                .with_bounds(BoundingBox2::from_points(Point2::new(x1, y1), Point2::new(x2, y2)))
+        };
+        // Overlapping pairs far from the cursor come first in the list
+        let mut entities: Vec<Entity> = (0..50)
+            .flat_map(|i| {
+                let x = 100.0 + i as f64 * 10.0;
+                [line(x, 0.0, x + 4.0, 4.0), line(x + 2.0, 0.0, x + 6.0, 4.0)]
+            })
+            .collect();
+        entities.push(line(0.0, 0.0, 4.0, 4.0));
+        entities.push(line(0.0, 4.0, 4.0, 0.0));
+        let indexed = IndexedEntities::from_entities(entities.clone());
+
+        let mut snap = ObjectSnap::new();
+        snap.mode.set(SnapMode::INTERSECTION);
+        let cursor = Point2::new(2.1, 1.9);
+        for result in [
+            snap.find_intersection(&entities, 0.5, cursor),
+            snap.find_intersection(&indexed, 0.5, cursor),
+        ] {
+            let result = result.unwrap();
+            assert!((result.point.x - 2.0).abs() < 1e-10);
+            match result.info {
+                SnapInfo::Intersection { entity1, entity2 } => {
+                    assert_eq!((entity1, entity2), (entities[100].id, entities[101].id));
+                }
+                other => panic!("unexpected snap {:?}", other),
+            }
+        }
+    }
+
     #[test]
     fn test_perpendicular_point() {
         let snap = ObjectSnap::new();
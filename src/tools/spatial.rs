@@ -0,0 +1,335 @@
+// Spatial Entity Lookup - index-backed candidate queries
+// Lets picking and snapping skip entities far from the cursor or pick ray
+
+use super::{BoundingBox2, Entity, EntityId, Point2, Ray3};
+use crate::core::primitives::{BoundingBox3 as CoreBox, Point3 as CorePoint, Ray3 as CoreRay};
+use crate::geometry::spatial::{ray_entry, SpatialIndex};
+use nalgebra::Vector3 as CoreVector;
+use std::collections::{HashMap, HashSet};
+
+/// Source of entities for picking and snapping
+///
+/// Queries return conservative candidate lists: every entity that could
+/// match, in a stable order, plus entities without bounds (which can't be
+/// culled). A plain slice scans linearly; [`IndexedEntities`] answers from a
+/// [`SpatialIndex`].
+pub trait EntitySource {
+    /// All entities
+    fn all(&self) -> Vec<&Entity>;
+
+    /// Entity with the given id
+    fn by_id(&self, id: &EntityId) -> Option<&Entity>;
+
+    /// Entities whose bounds come within `tolerance` of `point`
+    fn near(&self, point: Point2, tolerance: f64) -> Vec<&Entity>;
+
+    /// Entities whose bounds `ray` enters within `max_distance`
+    fn along_ray(&self, ray: &Ray3, max_distance: f64) -> Vec<&Entity>;
+
+    /// Bounded entity minimising `distance`, which must never be less than
+    /// the distance from `point` to the entity's bounds
+    fn nearest_by(
+        &self,
+        point: Point2,
+        distance: &mut dyn FnMut(&Entity) -> Option<f64>,
+    ) -> Option<(&Entity, f64)>;
+}
+
+impl EntitySource for [Entity] {
+    fn all(&self) -> Vec<&Entity> {
+        self.iter().collect()
+    }
+
+    fn by_id(&self, id: &EntityId) -> Option<&Entity> {
+        self.iter().find(|e| e.id == *id)
+    }
+
+    fn near(&self, point: Point2, tolerance: f64) -> Vec<&Entity> {
+        self.iter()
+            .filter(|e| e.bounds.is_none_or(|b| b.intersects(&point, tolerance)))
+            .collect()
+    }
+
+    fn along_ray(&self, ray: &Ray3, max_distance: f64) -> Vec<&Entity> {
+        let ray = to_core_ray(ray);
+        self.iter()
+            .filter(|e| {
+                e.bounds
+                    .is_none_or(|b| ray_entry(&ray, &to_core_box(&b)).is_some_and(|t| t <= max_distance))
+            })
+            .collect()
+    }
+
+    fn nearest_by(
+        &self,
+        _point: Point2,
+        distance: &mut dyn FnMut(&Entity) -> Option<f64>,
+    ) -> Option<(&Entity, f64)> {
+        let mut best: Option<(&Entity, f64)> = None;
+        for entity in self.iter().filter(|e| e.bounds.is_some()) {
+            if let Some(d) = distance(entity) {
+                if best.is_none_or(|(_, b)| d < b) {
+                    best = Some((entity, d));
+                }
+            }
+        }
+        best
+    }
+}
+
+impl EntitySource for Vec<Entity> {
+    fn all(&self) -> Vec<&Entity> {
+        self.as_slice().all()
+    }
+
+    fn by_id(&self, id: &EntityId) -> Option<&Entity> {
+        self.as_slice().by_id(id)
+    }
+
+    fn near(&self, point: Point2, tolerance: f64) -> Vec<&Entity> {
+        self.as_slice().near(point, tolerance)
+    }
+
+    fn along_ray(&self, ray: &Ray3, max_distance: f64) -> Vec<&Entity> {
+        self.as_slice().along_ray(ray, max_distance)
+    }
+
+    fn nearest_by(
+        &self,
+        point: Point2,
+        distance: &mut dyn FnMut(&Entity) -> Option<f64>,
+    ) -> Option<(&Entity, f64)> {
+        self.as_slice().nearest_by(point, distance)
+    }
+}
+
+/// Entity collection with a spatial index kept in sync on every change
+///
+/// Entities keep insertion order except that removal moves the last entity
+/// into the freed slot. Query results come back in that order, so picks and
+/// snaps resolve ties the same way a linear scan of [`entities`](Self::entities)
+/// would.
+#[derive(Debug, Clone, Default)]
+pub struct IndexedEntities {
+    entities: Vec<Entity>,
+    positions: HashMap<EntityId, usize>,
+    index: SpatialIndex<EntityId>,
+    unbounded: HashSet<EntityId>,
+}
+
+impl IndexedEntities {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index a batch of entities, bulk-loading the tree
+    pub fn from_entities(entities: Vec<Entity>) -> Self {
+        let mut indexed = Self::new();
+        for entity in entities {
+            match indexed.positions.get(&entity.id) {
+                Some(&position) => indexed.entities[position] = entity,
+                None => {
+                    indexed.positions.insert(entity.id, indexed.entities.len());
+                    indexed.entities.push(entity);
+                }
+            }
+        }
+        indexed.index = SpatialIndex::from_items(
+            indexed
+                .entities
+                .iter()
+                .filter_map(|e| e.bounds.map(|b| (e.id, to_core_box(&b)))),
+        );
+        indexed.unbounded = indexed
+            .entities
+            .iter()
+            .filter(|e| e.bounds.is_none())
+            .map(|e| e.id)
+            .collect();
+        indexed
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    pub fn get(&self, id: &EntityId) -> Option<&Entity> {
+        self.positions.get(id).map(|&p| &self.entities[p])
+    }
+
+    /// The underlying index
+    pub fn index(&self) -> &SpatialIndex<EntityId> {
+        &self.index
+    }
+
+    /// Add an entity, replacing any with the same id
+    pub fn insert(&mut self, entity: Entity) {
+        self.reindex(entity.id, entity.bounds);
+        match self.positions.get(&entity.id) {
+            Some(&position) => self.entities[position] = entity,
+            None => {
+                self.positions.insert(entity.id, self.entities.len());
+                self.entities.push(entity);
+            }
+        }
+    }
+
+    /// Remove an entity
+    pub fn remove(&mut self, id: &EntityId) -> Option<Entity> {
+        let position = self.positions.remove(id)?;
+        self.index.remove(id);
+        self.unbounded.remove(id);
+        let entity = self.entities.swap_remove(position);
+        if let Some(moved) = self.entities.get(position) {
+            self.positions.insert(moved.id, position);
+        }
+        Some(entity)
+    }
+
+    /// Change an entity in place; the index follows any change to its bounds
+    ///
+    /// The id is the index key, so any change `f` makes to it is undone.
+    pub fn modify<R>(&mut self, id: &EntityId, f: impl FnOnce(&mut Entity) -> R) -> Option<R> {
+        let position = *self.positions.get(id)?;
+        let entity = &mut self.entities[position];
+        let result = f(entity);
+        entity.id = *id;
+        let bounds = entity.bounds;
+        self.reindex(*id, bounds);
+        Some(result)
+    }
+
+    fn reindex(&mut self, id: EntityId, bounds: Option<BoundingBox2>) {
+        match bounds {
+            Some(bounds) => {
+                self.unbounded.remove(&id);
+                self.index.insert(id, to_core_box(&bounds));
+            }
+            None => {
+                self.index.remove(&id);
+                self.unbounded.insert(id);
+            }
+        }
+    }
+
+    /// Resolve ids to entities in storage order, with unbounded entities
+    fn resolve(&self, ids: impl IntoIterator<Item = EntityId>) -> Vec<&Entity> {
+        let mut positions: Vec<usize> = ids
+            .into_iter()
+            .chain(self.unbounded.iter().copied())
+            .filter_map(|id| self.positions.get(&id).copied())
+            .collect();
+        positions.sort_unstable();
+        positions.into_iter().map(|p| &self.entities[p]).collect()
+    }
+}
+
+impl EntitySource for IndexedEntities {
+    fn all(&self) -> Vec<&Entity> {
+        self.entities.iter().collect()
+    }
+
+    fn by_id(&self, id: &EntityId) -> Option<&Entity> {
+        self.get(id)
+    }
+
+    fn near(&self, point: Point2, tolerance: f64) -> Vec<&Entity> {
+        let region = CoreBox::new(
+            CorePoint::new(point.x - tolerance, point.y - tolerance, 0.0),
+            CorePoint::new(point.x + tolerance, point.y + tolerance, 0.0),
+        );
+        self.resolve(self.index.query_box(&region))
+    }
+
+    fn along_ray(&self, ray: &Ray3, max_distance: f64) -> Vec<&Entity> {
+        let hits = self.index.query_ray(&to_core_ray(ray), max_distance);
+        self.resolve(hits.into_iter().map(|(id, _)| id))
+    }
+
+    fn nearest_by(
+        &self,
+        point: Point2,
+        distance: &mut dyn FnMut(&Entity) -> Option<f64>,
+    ) -> Option<(&Entity, f64)> {
+        let point = CorePoint::new(point.x, point.y, 0.0);
+        let (id, d) = self
+            .index
+            .nearest_by(&point, f64::INFINITY, |id, _| self.get(&id).and_then(&mut *distance))?;
+        self.get(&id).map(|entity| (entity, d))
+    }
+}
+
+/// Tools bounds as a flat box at z = 0
+pub fn to_core_box(bounds: &BoundingBox2) -> CoreBox {
+    CoreBox::new(
+        CorePoint::new(bounds.min.x, bounds.min.y, 0.0),
+        CorePoint::new(bounds.max.x, bounds.max.y, 0.0),
+    )
+}
+
+pub fn to_core_ray(ray: &Ray3) -> CoreRay {
+    CoreRay::new(
+        CorePoint::new(ray.origin.x, ray.origin.y, ray.origin.z),
+        CoreVector::new(ray.direction.x, ray.direction.y, ray.direction.z),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{EntityType, Point3, Vector3};
+
+    fn boxed(x: f64, y: f64) -> Entity {
+        Entity::new(EntityType::Line).with_bounds(BoundingBox2::from_points(
+            Point2::new(x, y),
+            Point2::new(x + 1.0, y + 1.0),
+        ))
+    }
+
+    fn ids(entities: Vec<&Entity>) -> Vec<EntityId> {
+        entities.into_iter().map(|e| e.id).collect()
+    }
+
+    #[test]
+    fn test_indexed_matches_linear_scan() {
+        let mut entities: Vec<Entity> = (0..400)
+            .map(|i| boxed((i % 20) as f64 * 3.0, (i / 20) as f64 * 3.0))
+            .collect();
+        entities.push(Entity::new(EntityType::Text));
+        let mut indexed = IndexedEntities::from_entities(entities.clone());
+
+        let cursor = Point2::new(10.2, 13.5);
+        assert_eq!(ids(indexed.near(cursor, 2.0)), ids(entities.near(cursor, 2.0)));
+        assert_eq!(indexed.near(cursor, 2.0).len(), 5);
+
+        let ray = Ray3::new(Point3::new(-5.0, 6.5, 0.0), Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(ids(indexed.along_ray(&ray, 20.0)), ids(entities.along_ray(&ray, 20.0)));
+
+        // Edits keep the index in step with the entities
+        let moved = entities[0].id;
+        indexed.modify(&moved, |e| {
+            e.bounds = Some(BoundingBox2::from_points(Point2::new(10.0, 13.0), Point2::new(10.5, 14.0)))
+        });
+        let removed = entities[5 * 20 + 3].id;
+        assert!(indexed.remove(&removed).is_some());
+        let near = ids(indexed.near(cursor, 2.0));
+        assert!(near.contains(&moved));
+        assert!(!near.contains(&removed));
+        assert_eq!(near, ids(indexed.entities().near(cursor, 2.0)));
+
+        let mut center_distance = |e: &Entity| {
+            let b = e.bounds?;
+            Some(cursor.distance_to(&b.min.midpoint(&b.max)))
+        };
+        let (nearest, _) = indexed.nearest_by(cursor, &mut center_distance).unwrap();
+        assert_eq!(nearest.id, moved);
+    }
+}
@@ -0,0 +1,801 @@
+//! Trim and extend against boundary edges
+//!
+//! [`trim_to`] cuts a curve wherever the boundary edges cross it and removes
+//! the part the pick point is on, from the crossing before the pick to the
+//! crossing after it. [`extend_to`] lengthens the end of an open curve
+//! nearer the pick until it reaches the first boundary edge in its way:
+//! lines run straight on, arcs continue around their circle, polylines
+//! stretch their end segment and splines grow a straight tail along their
+//! end tangent.
+//!
+//! Targets and boundaries may be lines, arcs, circles, polylines or
+//! B-splines. Crossings come from [`crate::geometry::intersect`], so they
+//! lie on splines to within tolerance and a boundary touching the target is
+//! one cut rather than two; the pieces left behind are exact parts of the
+//! original spline, split by knot insertion. Picks are located, and spline
+//! ends extended, against [`SPLINE_SEGMENTS`] chords per knot span.
+
+use crate::core::precision::{EPSILON, EPSILON_ROUGH};
+use crate::geometry::closest::ClosestPoint;
+use crate::geometry::fillet::{intersect, sweep_to, Carrier};
+use crate::geometry::intersect::{intersect_with_tolerance, CurveRef};
+use crate::geometry::{Arc2D, BSpline, Circle2D, FitSegment, LineSegment2D, Point2D, Polyline2D};
+use std::f64::consts::{PI, TAU};
+
+/// Chords per knot span used to locate picks on splines and to extend to
+/// spline boundaries
+pub const SPLINE_SEGMENTS: usize = 32;
+
+/// Parameters closer than this fraction of the curve's domain are the same
+const PARAM_TOLERANCE: f64 = 1e-9;
+
+/// A curve that can be trimmed or extended, or serve as a boundary edge
+#[derive(Debug, Clone, PartialEq)]
+pub enum CurveEntity {
+    /// Line segment
+    Line(LineSegment2D),
+    /// Circular arc
+    Arc(Arc2D),
+    /// Full circle; trimming one leaves an arc
+    Circle(Circle2D),
+    /// Polyline; trimming a closed one leaves it open
+    Polyline(Polyline2D),
+    /// B-spline
+    Spline(BSpline),
+}
+
+/// Trim `target` back to the boundary edges either side of `pick`
+///
+/// Returns what is left of the target: one or two curves for an open
+/// target, one for a closed one, which needs two crossings to be cut.
+/// Returns `None` if no boundary crosses the picked part, so there is
+/// nothing to remove. A target listed among its own boundaries is skipped.
+pub fn trim_to(target: &CurveEntity, boundaries: &[CurveEntity], pick: Point2D) -> Option<Vec<CurveEntity>> {
+    let (start, end) = target.domain();
+    let tolerance = (end - start) * PARAM_TOLERANCE;
+    let at = target.locate(pick)?.param;
+    let mut cuts = target.crossings(boundaries);
+
+    if target.is_closed() {
+        let period = end - start;
+        for cut in &mut cuts {
+            *cut = start + (*cut - start).rem_euclid(period);
+        }
+        cuts.sort_by(f64::total_cmp);
+        cuts.dedup_by(|a, b| (*a - *b).abs() < tolerance);
+        if cuts.len() > 1 && cuts[0] + period - cuts[cuts.len() - 1] < tolerance {
+            cuts.pop();
+        }
+        if cuts.len() < 2 {
+            return None;
+        }
+        let after = cuts.iter().copied().find(|&t| t > at).unwrap_or(cuts[0] + period);
+        let before = cuts.iter().rev().copied().find(|&t| t <= at).unwrap_or(cuts[cuts.len() - 1] - period);
+        return target.between(after, before + period).map(|kept| vec![kept]);
+    }
+
+    let before = cuts.iter().rev().copied().find(|&t| t < at && t > start + tolerance);
+    let after = cuts.iter().copied().find(|&t| t > at && t < end - tolerance);
+    if before.is_none() && after.is_none() {
+        return None;
+    }
+    let mut kept = Vec::new();
+    kept.extend(before.and_then(|t| target.between(start, t)));
+    kept.extend(after.and_then(|t| target.between(t, end)));
+    Some(kept)
+}
+
+/// Extend the end of `target` nearer `pick` to the first boundary edge
+///
+/// Returns `None` for closed targets, splines that are not clamped at that
+/// end, and when nothing lies in the extension's path.
+pub fn extend_to(target: &CurveEntity, boundaries: &[CurveEntity], pick: Point2D) -> Option<CurveEntity> {
+    if target.is_closed() {
+        return None;
+    }
+    let at = target.locate(pick)?;
+    let boundaries: Vec<&CurveEntity> = boundaries.iter().filter(|b| *b != target).collect();
+    if at.along * 2.0 < target.length() {
+        extend_end(&target.reversed(), &boundaries).map(|extended| extended.reversed())
+    } else {
+        extend_end(target, &boundaries)
+    }
+}
+
+/// Extend the end of an open curve
+fn extend_end(target: &CurveEntity, boundaries: &[&CurveEntity]) -> Option<CurveEntity> {
+    match target {
+        CurveEntity::Line(line) => {
+            let (_, hit) = ray_hit(line.end, (line.end - line.start).normalize(), boundaries)?;
+            Some(CurveEntity::Line(LineSegment2D::new(line.start, hit)))
+        }
+        CurveEntity::Arc(arc) => {
+            let carrier = Carrier::Circle {
+                center: arc.center,
+                radius: arc.radius,
+            };
+            let room = TAU - arc.sweep_angle();
+            let slack = EPSILON_ROUGH / arc.radius;
+            let turn = nearest_hit(&carrier, boundaries, |p| {
+                let angle = arc.center.angle_to(&p);
+                let turn = if arc.ccw {
+                    angle - arc.end_angle
+                } else {
+                    arc.end_angle - angle
+                }
+                .rem_euclid(TAU);
+                (turn > slack && turn < room - slack).then_some(turn)
+            })?
+            .0;
+            let end_angle = if arc.ccw { arc.end_angle + turn } else { arc.end_angle - turn };
+            Some(CurveEntity::Arc(Arc2D::new(arc.center, arc.radius, arc.start_angle, end_angle, arc.ccw)))
+        }
+        CurveEntity::Polyline(polyline) => {
+            let last = *polyline.vertices.last()?;
+            let before = polyline
+                .vertices
+                .iter()
+                .rev()
+                .find(|v| v.distance_to(&last) > EPSILON)?;
+            let (_, hit) = ray_hit(last, (last - *before).normalize(), boundaries)?;
+            let mut vertices = polyline.vertices.clone();
+            *vertices.last_mut()? = hit;
+            Some(CurveEntity::Polyline(Polyline2D::new(vertices, false)))
+        }
+        CurveEntity::Spline(spline) => {
+            let (end, dir, speed) = clamped_end(spline)?;
+            let (distance, _) = ray_hit(end, dir, boundaries)?;
+            Some(CurveEntity::Spline(extend_spline(spline, dir, distance, speed)))
+        }
+        CurveEntity::Circle(_) => None,
+    }
+}
+
+/// First boundary crossing on the ray from `origin` along unit `dir`
+fn ray_hit(origin: Point2D, dir: Point2D, boundaries: &[&CurveEntity]) -> Option<(f64, Point2D)> {
+    let carrier = Carrier::Line { point: origin, dir };
+    nearest_hit(&carrier, boundaries, |p| {
+        let distance = (p - origin).dot(&dir);
+        (distance > EPSILON_ROUGH).then_some(distance)
+    })
+}
+
+/// Boundary crossing on `carrier` with the smallest cost; `cost` rejects
+/// points behind the end being extended
+fn nearest_hit<F>(carrier: &Carrier, boundaries: &[&CurveEntity], cost: F) -> Option<(f64, Point2D)>
+where
+    F: Fn(Point2D) -> Option<f64>,
+{
+    boundaries
+        .iter()
+        .flat_map(|boundary| boundary.pieces())
+        .flat_map(|edge| {
+            intersect(carrier, &Carrier::of(&edge.segment))
+                .into_iter()
+                .filter(move |p| edge.param_of(*p).is_some())
+        })
+        .filter_map(|p| cost(p).map(|c| (c, p)))
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+/// Where a pick lands on a curve
+struct Location {
+    /// Curve parameter of the closest point
+    param: f64,
+    /// Length along the curve to the closest point
+    along: f64,
+}
+
+/// Part of a curve between two of its parameters, as a line or arc
+struct Piece {
+    segment: FitSegment,
+    from: f64,
+    to: f64,
+}
+
+impl Piece {
+    /// Curve parameter of a point on the piece, if it lies within it
+    fn param_of(&self, p: Point2D) -> Option<f64> {
+        let fraction = match &self.segment {
+            FitSegment::Line(line) => {
+                let d = line.end - line.start;
+                let length = d.distance_to_origin();
+                let f = (p - line.start).dot(&d) / (length * length);
+                let slack = EPSILON_ROUGH / length;
+                if f < -slack || f > 1.0 + slack {
+                    return None;
+                }
+                f
+            }
+            FitSegment::Arc(arc) => {
+                let sweep = arc.sweep_angle();
+                let slack = EPSILON_ROUGH / arc.radius;
+                let swept = sweep_to(arc, arc.center.angle_to(&p));
+                if swept > TAU - slack {
+                    0.0
+                } else if swept > sweep + slack {
+                    return None;
+                } else {
+                    swept / sweep
+                }
+            }
+        };
+        Some(self.at(fraction.clamp(0.0, 1.0)))
+    }
+
+    /// Distance to the closest point and its fraction along the piece
+    fn nearest(&self, p: Point2D) -> (f64, f64) {
+        let Some(closest) = self.segment.closest_to_point(&p) else {
+            return (f64::INFINITY, 0.0);
+        };
+        let fraction = match &self.segment {
+            FitSegment::Line(_) => closest.parameter,
+            FitSegment::Arc(arc) if arc.sweep_angle() > 0.0 => closest.parameter / arc.sweep_angle(),
+            FitSegment::Arc(_) => 0.0,
+        };
+        (closest.distance(), fraction)
+    }
+
+    fn at(&self, fraction: f64) -> f64 {
+        self.from + fraction * (self.to - self.from)
+    }
+
+    fn length(&self) -> f64 {
+        match &self.segment {
+            FitSegment::Line(line) => line.length(),
+            FitSegment::Arc(arc) => arc.length(),
+        }
+    }
+}
+
+impl CurveEntity {
+    /// Whether the curve has no ends
+    pub fn is_closed(&self) -> bool {
+        match self {
+            CurveEntity::Circle(_) => true,
+            CurveEntity::Polyline(polyline) => polyline.closed,
+            _ => false,
+        }
+    }
+
+    /// Length of the curve (of its chords, for splines)
+    pub fn length(&self) -> f64 {
+        self.pieces().iter().map(Piece::length).sum()
+    }
+
+    /// Parameter domain: a fraction for lines, the angle swept from the
+    /// start for arcs and circles, segment index plus fraction for
+    /// polylines and the knot parameter for splines
+    fn domain(&self) -> (f64, f64) {
+        match self {
+            CurveEntity::Line(_) => (0.0, 1.0),
+            CurveEntity::Arc(arc) => (0.0, arc.sweep_angle()),
+            CurveEntity::Circle(_) => (0.0, TAU),
+            CurveEntity::Polyline(polyline) => (0.0, segment_count(polyline) as f64),
+            CurveEntity::Spline(spline) => spline.parameter_range(),
+        }
+    }
+
+    fn point_at(&self, t: f64) -> Point2D {
+        match self {
+            CurveEntity::Line(line) => line.start + (line.end - line.start) * t,
+            CurveEntity::Arc(arc) => arc.point_at_angle(arc_angle(arc, t)),
+            CurveEntity::Circle(circle) => circle.center + Point2D::from_polar(circle.radius, t),
+            CurveEntity::Polyline(polyline) => {
+                let n = polyline.vertices.len();
+                let segments = segment_count(polyline);
+                let t = if polyline.closed { t.rem_euclid(segments as f64) } else { t };
+                let i = (t.floor().max(0.0) as usize).min(segments - 1);
+                let (a, b) = (polyline.vertices[i], polyline.vertices[(i + 1) % n]);
+                a + (b - a) * (t - i as f64)
+            }
+            CurveEntity::Spline(spline) => spline.evaluate(t),
+        }
+    }
+
+    /// Lines and arcs covering the curve, tagged with their parameters
+    fn pieces(&self) -> Vec<Piece> {
+        match self {
+            CurveEntity::Line(line) => vec![Piece {
+                segment: FitSegment::Line(*line),
+                from: 0.0,
+                to: 1.0,
+            }],
+            CurveEntity::Arc(arc) => vec![Piece {
+                segment: FitSegment::Arc(*arc),
+                from: 0.0,
+                to: arc.sweep_angle(),
+            }],
+            CurveEntity::Circle(circle) => vec![
+                Piece {
+                    segment: FitSegment::Arc(Arc2D::new(circle.center, circle.radius, 0.0, PI, true)),
+                    from: 0.0,
+                    to: PI,
+                },
+                Piece {
+                    segment: FitSegment::Arc(Arc2D::new(circle.center, circle.radius, PI, 0.0, true)),
+                    from: PI,
+                    to: TAU,
+                },
+            ],
+            CurveEntity::Polyline(polyline) => (0..segment_count(polyline))
+                .map(|i| Piece {
+                    segment: FitSegment::Line(LineSegment2D::new(self.point_at(i as f64), self.point_at(i as f64 + 1.0))),
+                    from: i as f64,
+                    to: i as f64 + 1.0,
+                })
+                .collect(),
+            CurveEntity::Spline(spline) => {
+                let (lo, hi) = spline.parameter_range();
+                let mut params = Vec::new();
+                for span in spline.knots.windows(2) {
+                    let (a, b) = (span[0].max(lo), span[1].min(hi));
+                    if b > a {
+                        params.extend((0..SPLINE_SEGMENTS).map(|i| a + (b - a) * i as f64 / SPLINE_SEGMENTS as f64));
+                    }
+                }
+                params.push(hi);
+                params
+                    .windows(2)
+                    .map(|w| Piece {
+                        segment: FitSegment::Line(LineSegment2D::new(spline.evaluate(w[0]), spline.evaluate(w[1]))),
+                        from: w[0],
+                        to: w[1],
+                    })
+                    .collect()
+            }
+        }
+        .into_iter()
+        .filter(|piece| piece.length() > EPSILON)
+        .collect()
+    }
+
+    /// Closest point to `p`
+    fn locate(&self, p: Point2D) -> Option<Location> {
+        let mut along = 0.0;
+        let mut best: Option<(f64, Location)> = None;
+        for piece in self.pieces() {
+            let (distance, fraction) = piece.nearest(p);
+            if best.as_ref().is_none_or(|(d, _)| distance < *d) {
+                best = Some((
+                    distance,
+                    Location {
+                        param: piece.at(fraction),
+                        along: along + fraction * piece.length(),
+                    },
+                ));
+            }
+            along += piece.length();
+        }
+        best.map(|(_, location)| location)
+    }
+
+    /// Call `f` with each part of the curve as the intersection solver takes
+    /// it, and a map from the part's parameter to the curve's
+    fn each_solver_curve(&self, mut f: impl FnMut(CurveRef, &dyn Fn(f64) -> f64)) {
+        match self {
+            CurveEntity::Line(line) => f(line.into(), &|t| t),
+            CurveEntity::Arc(arc) => f(arc.into(), &|t| t),
+            CurveEntity::Circle(circle) => f(circle.into(), &|t| t),
+            CurveEntity::Spline(spline) => f(spline.into(), &|t| t),
+            CurveEntity::Polyline(_) => {
+                for piece in self.pieces() {
+                    if let FitSegment::Line(line) = &piece.segment {
+                        f(line.into(), &|t| piece.at(t));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sorted parameters where boundaries other than the curve itself cross it
+    fn crossings(&self, boundaries: &[CurveEntity]) -> Vec<f64> {
+        let mut params = Vec::new();
+        for boundary in boundaries.iter().filter(|b| *b != self) {
+            self.each_solver_curve(|target, to_param| {
+                boundary.each_solver_curve(|edge, _| {
+                    let hits = intersect_with_tolerance(target, edge, EPSILON_ROUGH);
+                    params.extend(hits.iter().map(|hit| to_param(hit.param_a)));
+                });
+            });
+        }
+        let (start, end) = self.domain();
+        let tolerance = (end - start) * PARAM_TOLERANCE;
+        params.sort_by(f64::total_cmp);
+        params.dedup_by(|a, b| (*a - *b).abs() < tolerance);
+        params
+    }
+
+    /// The part of the curve from `a` to `b`; on closed curves `b` may run
+    /// past the end of the domain and wrap around
+    fn between(&self, a: f64, b: f64) -> Option<CurveEntity> {
+        let (start, end) = self.domain();
+        if b - a < (end - start) * PARAM_TOLERANCE {
+            return None;
+        }
+        Some(match self {
+            CurveEntity::Line(_) => CurveEntity::Line(LineSegment2D::new(self.point_at(a), self.point_at(b))),
+            CurveEntity::Arc(arc) => {
+                CurveEntity::Arc(Arc2D::new(arc.center, arc.radius, arc_angle(arc, a), arc_angle(arc, b), arc.ccw))
+            }
+            CurveEntity::Circle(circle) => CurveEntity::Arc(Arc2D::new(circle.center, circle.radius, a, b, true)),
+            CurveEntity::Polyline(polyline) => {
+                let n = polyline.vertices.len();
+                let mut vertices = vec![self.point_at(a)];
+                let mut i = a.floor() as usize + 1;
+                while (i as f64) < b - PARAM_TOLERANCE {
+                    vertices.push(polyline.vertices[i % n]);
+                    i += 1;
+                }
+                vertices.push(self.point_at(b));
+                CurveEntity::Polyline(Polyline2D::new(vertices, false))
+            }
+            CurveEntity::Spline(spline) => {
+                let tolerance = (end - start) * PARAM_TOLERANCE;
+                let mut piece = spline.clone();
+                if b < end - tolerance {
+                    piece = split_spline(&piece, b).0;
+                }
+                if a > start + tolerance {
+                    piece = split_spline(&piece, a).1;
+                }
+                CurveEntity::Spline(piece)
+            }
+        })
+    }
+
+    /// The same curve running the other way
+    fn reversed(&self) -> CurveEntity {
+        match self {
+            CurveEntity::Line(line) => CurveEntity::Line(LineSegment2D::new(line.end, line.start)),
+            CurveEntity::Arc(arc) => {
+                CurveEntity::Arc(Arc2D::new(arc.center, arc.radius, arc.end_angle, arc.start_angle, !arc.ccw))
+            }
+            CurveEntity::Circle(circle) => CurveEntity::Circle(*circle),
+            CurveEntity::Polyline(polyline) => {
+                CurveEntity::Polyline(Polyline2D::new(polyline.vertices.iter().rev().copied().collect(), polyline.closed))
+            }
+            CurveEntity::Spline(spline) => {
+                let (first, last) = (spline.knots[0], spline.knots[spline.knots.len() - 1]);
+                CurveEntity::Spline(BSpline {
+                    control_points: spline.control_points.iter().rev().copied().collect(),
+                    knots: spline.knots.iter().rev().map(|u| first + last - u).collect(),
+                    degree: spline.degree,
+                })
+            }
+        }
+    }
+}
+
+fn segment_count(polyline: &Polyline2D) -> usize {
+    let n = polyline.vertices.len();
+    if polyline.closed && n > 2 {
+        n
+    } else {
+        n.saturating_sub(1)
+    }
+}
+
+/// Angle reached after sweeping `t` from the start of `arc`
+fn arc_angle(arc: &Arc2D, t: f64) -> f64 {
+    if arc.ccw {
+        arc.start_angle + t
+    } else {
+        arc.start_angle - t
+    }
+}
+
+/// Split a spline at an interior parameter by inserting the knot until it
+/// has multiplicity `degree`, leaving both halves clamped at the cut
+fn split_spline(spline: &BSpline, t: f64) -> (BSpline, BSpline) {
+    let p = spline.degree;
+    let (lo, hi) = spline.parameter_range();
+    let tolerance = (hi - lo) * PARAM_TOLERANCE;
+    // Snap to an existing knot so its multiplicity counts
+    let t = spline
+        .knots
+        .iter()
+        .copied()
+        .find(|u| (u - t).abs() < tolerance)
+        .unwrap_or(t);
+    let multiplicity = spline.knots.iter().filter(|&&u| u == t).count();
+    let mut spline = spline.clone();
+    for _ in multiplicity..p {
+        spline = insert_knot(&spline, t);
+    }
+
+    let k = spline.find_knot_span(t);
+    let left = BSpline {
+        control_points: spline.control_points[..=k - p].to_vec(),
+        knots: spline.knots[..=k].iter().copied().chain([t]).collect(),
+        degree: p,
+    };
+    let right = BSpline {
+        control_points: spline.control_points[k - p..].to_vec(),
+        knots: [t].into_iter().chain(spline.knots[k - p + 1..].iter().copied()).collect(),
+        degree: p,
+    };
+    (left, right)
+}
+
+/// Insert one knot without changing the curve's shape (Boehm's algorithm)
+fn insert_knot(spline: &BSpline, t: f64) -> BSpline {
+    let p = spline.degree;
+    let k = spline.find_knot_span(t);
+    let (points, knots) = (&spline.control_points, &spline.knots);
+
+    let mut control_points = Vec::with_capacity(points.len() + 1);
+    control_points.extend_from_slice(&points[..=k - p]);
+    for i in k - p + 1..=k {
+        let alpha = (t - knots[i]) / (knots[i + p] - knots[i]);
+        control_points.push(points[i - 1] * (1.0 - alpha) + points[i] * alpha);
+    }
+    control_points.extend_from_slice(&points[k..]);
+
+    BSpline {
+        control_points,
+        knots: knots[..=k].iter().copied().chain([t]).chain(knots[k + 1..].iter().copied()).collect(),
+        degree: p,
+    }
+}
+
+/// End point, unit end tangent and parametric speed at the end of a spline
+/// clamped there
+fn clamped_end(spline: &BSpline) -> Option<(Point2D, Point2D, f64)> {
+    let p = spline.degree;
+    let n = spline.control_points.len().checked_sub(1)?;
+    if p == 0 || n == 0 {
+        return None;
+    }
+    let last = spline.knots[n + p + 1];
+    if spline.knots[n + 1..].iter().any(|&u| u != last) {
+        return None;
+    }
+    let (end, previous) = (spline.control_points[n], spline.control_points[n - 1]);
+    let chord = end - previous;
+    let span = last - spline.knots[n];
+    if chord.distance_to_origin() < EPSILON || span < EPSILON {
+        return None;
+    }
+    Some((end, chord.normalize(), p as f64 * chord.distance_to_origin() / span))
+}
+
+/// Append a straight tail of `length` along `dir` to a spline clamped at
+/// its end, running on at the same parametric speed
+fn extend_spline(spline: &BSpline, dir: Point2D, length: f64, speed: f64) -> BSpline {
+    let p = spline.degree;
+    let end = spline.control_points[spline.control_points.len() - 1];
+    let last = spline.knots[spline.knots.len() - 1];
+    let new_last = last + length / speed;
+
+    let mut control_points = spline.control_points.clone();
+    control_points.extend((1..=p).map(|i| end + dir * (length * i as f64 / p as f64)));
+    let mut knots = spline.knots[..spline.knots.len() - 1].to_vec();
+    knots.extend(std::iter::repeat_n(new_last, p + 1));
+
+    BSpline {
+        control_points,
+        knots,
+        degree: p,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(x1: f64, y1: f64, x2: f64, y2: f64) -> CurveEntity {
+        CurveEntity::Line(LineSegment2D::new(Point2D::new(x1, y1), Point2D::new(x2, y2)))
+    }
+
+    fn endpoints(curve: &CurveEntity) -> (Point2D, Point2D) {
+        let (start, end) = curve.domain();
+        (curve.point_at(start), curve.point_at(end))
+    }
+
+    fn close(a: Point2D, b: Point2D, tolerance: f64) -> bool {
+        a.distance_to(&b) < tolerance
+    }
+
+    #[test]
+    fn test_trim_line_between_two_edges() {
+        let target = line(0.0, 0.0, 10.0, 0.0);
+        let edges = [line(3.0, -1.0, 3.0, 1.0), line(7.0, -1.0, 7.0, 1.0)];
+
+        let kept = trim_to(&target, &edges, Point2D::new(5.0, 0.1)).unwrap();
+        assert_eq!(kept.len(), 2);
+        assert_eq!(endpoints(&kept[0]), (Point2D::new(0.0, 0.0), Point2D::new(3.0, 0.0)));
+        assert_eq!(endpoints(&kept[1]), (Point2D::new(7.0, 0.0), Point2D::new(10.0, 0.0)));
+
+        // Picking the end beyond the last edge removes just that end
+        let kept = trim_to(&target, &edges, Point2D::new(9.0, 0.0)).unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(endpoints(&kept[0]), (Point2D::new(0.0, 0.0), Point2D::new(7.0, 0.0)));
+    }
+
+    #[test]
+    fn test_trim_needs_a_crossing() {
+        let target = line(0.0, 0.0, 10.0, 0.0);
+        assert!(trim_to(&target, &[line(3.0, 1.0, 3.0, 2.0)], Point2D::new(5.0, 0.0)).is_none());
+        // The target as its own boundary does not count
+        assert!(trim_to(&target, std::slice::from_ref(&target), Point2D::new(5.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_trim_at_tangent_boundary() {
+        // A circle resting on the line cuts it once, at the point of contact
+        let target = line(0.0, 0.0, 10.0, 0.0);
+        let edges = [
+            CurveEntity::Circle(Circle2D::new(Point2D::new(4.0, 2.0), 2.0)),
+            line(8.0, -1.0, 8.0, 1.0),
+        ];
+        let kept = trim_to(&target, &edges, Point2D::new(6.0, 0.0)).unwrap();
+        assert_eq!(kept.len(), 2);
+        assert!(close(endpoints(&kept[0]).1, Point2D::new(4.0, 0.0), 1e-9));
+        assert!(close(endpoints(&kept[1]).0, Point2D::new(8.0, 0.0), 1e-9));
+    }
+
+    #[test]
+    fn test_trim_circle_leaves_arc() {
+        let target = CurveEntity::Circle(Circle2D::new(Point2D::new(0.0, 0.0), 5.0));
+        let edges = [line(0.0, -10.0, 0.0, 10.0)];
+
+        let kept = trim_to(&target, &edges, Point2D::new(6.0, 0.0)).unwrap();
+        assert_eq!(kept.len(), 1);
+        let CurveEntity::Arc(arc) = kept[0] else { panic!("expected an arc") };
+        assert!((arc.sweep_angle() - PI).abs() < 1e-9);
+        assert!(close(arc.point_at_angle(PI), Point2D::new(-5.0, 0.0), 1e-9));
+        assert!(close(arc.start_point(), Point2D::new(0.0, -5.0), 1e-9) || close(arc.start_point(), Point2D::new(0.0, 5.0), 1e-9));
+        assert!(arc.contains_angle(PI));
+        assert!(!arc.contains_angle(0.0));
+    }
+
+    #[test]
+    fn test_trim_arc_and_closed_polyline() {
+        // Quarter arc cut at 45 degrees, picked near its start
+        let arc = CurveEntity::Arc(Arc2D::new(Point2D::new(0.0, 0.0), 10.0, 0.0, PI / 2.0, true));
+        let kept = trim_to(&arc, &[line(0.0, 0.0, 20.0, 20.0)], Point2D::new(10.0, 1.0)).unwrap();
+        let CurveEntity::Arc(rest) = kept[0] else { panic!("expected an arc") };
+        assert!((rest.sweep_angle() - PI / 4.0).abs() < 1e-9);
+        assert!(close(rest.end_point(), Point2D::new(0.0, 10.0), 1e-9));
+
+        // A square cut across its top opens up without the top edge
+        let square = CurveEntity::Polyline(Polyline2D::closed(vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(10.0, 0.0),
+            Point2D::new(10.0, 10.0),
+            Point2D::new(0.0, 10.0),
+        ]));
+        let kept = trim_to(&square, &[line(-1.0, 5.0, 11.0, 5.0)], Point2D::new(5.0, 10.0)).unwrap();
+        let CurveEntity::Polyline(open) = &kept[0] else { panic!("expected a polyline") };
+        assert!(!open.closed);
+        assert_eq!(
+            open.vertices,
+            vec![
+                Point2D::new(0.0, 5.0),
+                Point2D::new(0.0, 0.0),
+                Point2D::new(10.0, 0.0),
+                Point2D::new(10.0, 5.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trim_spline_keeps_shape() {
+        let spline = BSpline::clamped(
+            vec![
+                Point2D::new(0.0, 0.0),
+                Point2D::new(2.0, 4.0),
+                Point2D::new(5.0, -2.0),
+                Point2D::new(8.0, 3.0),
+                Point2D::new(10.0, 0.0),
+            ],
+            3,
+        )
+        .unwrap();
+        let target = CurveEntity::Spline(spline.clone());
+        let kept = trim_to(&target, &[line(5.0, -10.0, 5.0, 10.0)], Point2D::new(9.0, 1.0)).unwrap();
+        assert_eq!(kept.len(), 1);
+        let CurveEntity::Spline(left) = &kept[0] else { panic!("expected a spline") };
+
+        let (lo, hi) = left.parameter_range();
+        assert!(close(left.evaluate(lo), spline.evaluate(lo), 1e-9));
+        assert!((left.evaluate(hi).x - 5.0).abs() < 1e-9);
+        let mid = (lo + hi) / 2.0;
+        assert!(close(left.evaluate(mid), spline.evaluate(mid), 1e-9));
+    }
+
+    #[test]
+    fn test_extend_line_and_arc() {
+        let wall = [line(10.0, -5.0, 10.0, 5.0), line(20.0, -5.0, 20.0, 5.0)];
+
+        let extended = extend_to(&line(0.0, 0.0, 4.0, 0.0), &wall, Point2D::new(3.0, 0.0)).unwrap();
+        assert_eq!(endpoints(&extended), (Point2D::new(0.0, 0.0), Point2D::new(10.0, 0.0)));
+
+        // Picked near its start, the line grows backwards instead
+        let extended = extend_to(&line(12.0, 0.0, 15.0, 0.0), &wall, Point2D::new(12.5, 0.0)).unwrap();
+        assert_eq!(endpoints(&extended), (Point2D::new(10.0, 0.0), Point2D::new(15.0, 0.0)));
+
+        // Nothing ahead
+        assert!(extend_to(&line(0.0, 0.0, 0.0, 4.0), &wall, Point2D::new(0.0, 3.0)).is_none());
+
+        // Clockwise arc from 90 degrees continues round to the x axis
+        let arc = CurveEntity::Arc(Arc2D::new(Point2D::new(0.0, 0.0), 5.0, PI / 2.0, PI / 4.0, false));
+        let extended = extend_to(&arc, &[line(0.0, 0.0, 10.0, 0.0)], Point2D::new(3.6, 3.4)).unwrap();
+        let CurveEntity::Arc(extended) = extended else { panic!("expected an arc") };
+        assert!(!extended.ccw);
+        assert!(close(extended.end_point(), Point2D::new(5.0, 0.0), 1e-9));
+        assert!((extended.sweep_angle() - PI / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_extend_polyline_start() {
+        let polyline = CurveEntity::Polyline(Polyline2D::open(vec![
+            Point2D::new(2.0, 2.0),
+            Point2D::new(2.0, 8.0),
+            Point2D::new(8.0, 8.0),
+        ]));
+        let extended = extend_to(&polyline, &[line(-5.0, 0.0, 5.0, 0.0)], Point2D::new(2.0, 3.0)).unwrap();
+        let CurveEntity::Polyline(extended) = extended else { panic!("expected a polyline") };
+        assert_eq!(extended.vertices[0], Point2D::new(2.0, 0.0));
+        assert_eq!(extended.vertices[2], Point2D::new(8.0, 8.0));
+    }
+
+    #[test]
+    fn test_extend_spline_along_tangent() {
+        let spline = BSpline::clamped(
+            vec![
+                Point2D::new(0.0, 0.0),
+                Point2D::new(1.0, 2.0),
+                Point2D::new(3.0, 2.0),
+                Point2D::new(4.0, 1.0),
+            ],
+            2,
+        )
+        .unwrap();
+        let target = CurveEntity::Spline(spline.clone());
+        let extended = extend_to(&target, &[line(10.0, -10.0, 10.0, 10.0)], Point2D::new(4.0, 1.0)).unwrap();
+        let CurveEntity::Spline(extended) = extended else { panic!("expected a spline") };
+
+        // Old part untouched, new end on the boundary along the end tangent
+        let (lo, hi) = spline.parameter_range();
+        for t in [lo, (lo + hi) / 2.0, hi] {
+            assert!(close(extended.evaluate(t), spline.evaluate(t), 1e-9));
+        }
+        let end = extended.evaluate(extended.parameter_range().1);
+        assert!(close(end, Point2D::new(10.0, -5.0), 1e-9));
+
+        // The tail carries on at the same speed, so the joint is smooth
+        let h = 1e-6;
+        let before = (spline.evaluate(hi) - spline.evaluate(hi - h)) / h;
+        let after = (extended.evaluate(hi + h) - extended.evaluate(hi)) / h;
+        assert!(close(before, after, 1e-4));
+    }
+
+    #[test]
+    fn test_split_spline_is_exact() {
+        let spline = BSpline::clamped(
+            vec![
+                Point2D::new(0.0, 0.0),
+                Point2D::new(1.0, 3.0),
+                Point2D::new(4.0, 3.0),
+                Point2D::new(6.0, -1.0),
+                Point2D::new(9.0, 2.0),
+            ],
+            3,
+        )
+        .unwrap();
+        // Inside a span and at an existing knot
+        for t in [0.7, 1.0] {
+            let (left, right) = split_spline(&spline, t);
+            assert!(close(left.evaluate(t), spline.evaluate(t), 1e-9));
+            assert!(close(right.evaluate(t), spline.evaluate(t), 1e-9));
+            for s in [0.1, 0.4] {
+                assert!(close(left.evaluate(s), spline.evaluate(s), 1e-9));
+            }
+            for s in [1.3, 1.9] {
+                assert!(close(right.evaluate(s), spline.evaluate(s), 1e-9));
+            }
+        }
+    }
+}
@@ -3,9 +3,13 @@
 /// This module implements the main application state and lifecycle.
 use egui::Context;
 
+use crate::io::parameters::ParameterValues;
+use crate::io::regional::Region;
+
 use super::{
     Canvas, CommandLine, StatusBar, DrawToolbar, ModifyToolbar, ViewToolbar,
-    PropertiesPanel, LayersPanel, CommandPanel, UiState, theme::CaddyTheme,
+    PropertiesPanel, LayersPanel, CommandPanel, RecycleBinPanel, DrawingHealthPanel, BlockLibraryPanel,
+    UiState, theme::CaddyTheme,
     toolbar::Toolbar, panel::Panel,
 };
 
@@ -41,6 +45,15 @@ pub struct CaddyApp {
     /// Command history panel
     command_panel: CommandPanel,
 
+    /// Recycle bin panel
+    recycle_bin_panel: RecycleBinPanel,
+
+    /// Drawing health panel
+    drawing_health_panel: DrawingHealthPanel,
+
+    /// Block library panel
+    block_library_panel: BlockLibraryPanel,
+
     /// Document title
     document_title: String,
 
@@ -55,15 +68,30 @@ pub struct CaddyApp {
 
     /// Frame counter for animations
     frame_count: u64,
+
+    /// Drafting region new documents are set up for, None until chosen at first run
+    region: Option<Region>,
+
+    /// Region selected in the first-run window
+    region_choice: Region,
 }
 
+/// Storage key of the chosen drafting region
+const REGION_KEY: &str = "caddy.region";
+
 impl CaddyApp {
     /// Create a new CADDY application
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         // Apply dark theme
         CaddyTheme::apply(&cc.egui_ctx, true);
 
-        Self {
+        let region = cc
+            .storage
+            .and_then(|storage| storage.get_string(REGION_KEY))
+            .and_then(|value| serde_json::from_str(&value).ok());
+        let locale = std::env::var("LC_ALL").or_else(|_| std::env::var("LANG")).unwrap_or_default();
+
+        let mut app = Self {
             ui_state: UiState::default(),
             canvas: Canvas::new(),
             command_line: CommandLine::new(),
@@ -74,11 +102,77 @@ impl CaddyApp {
             properties_panel: PropertiesPanel::new(),
             layers_panel: LayersPanel::new(),
             command_panel: CommandPanel::new(),
+            recycle_bin_panel: RecycleBinPanel::new(),
+            drawing_health_panel: DrawingHealthPanel::new(),
+            block_library_panel: BlockLibraryPanel::new(),
             document_title: "Untitled".to_string(),
             document_modified: false,
             current_file: None,
             active_command: None,
             frame_count: 0,
+            region: None,
+            region_choice: Region::from_locale(&locale),
+        };
+        if let Some(region) = region {
+            app.set_region(region);
+        }
+        app
+    }
+
+    /// Region new documents are set up for, once chosen
+    pub fn region(&self) -> Option<Region> {
+        self.region
+    }
+
+    /// Set up new documents and the status bar for a drafting region
+    pub fn set_region(&mut self, region: Region) {
+        let preset = region.preset();
+        self.status_bar.set_units(preset.units);
+        self.command_line.set_values(ParameterValues::empty(preset.units));
+        self.properties_panel.set_values(ParameterValues::empty(preset.units));
+        self.region = Some(region);
+        self.region_choice = region;
+        log::info!("Drafting region set to {}", region);
+    }
+
+    /// First-run window choosing the region's units, paper and standards
+    fn show_region_window(&mut self, ctx: &Context) {
+        let mut chosen = None;
+        egui::Window::new("Welcome to CADDY")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("Choose the drafting conventions new drawings start with.");
+                ui.add_space(8.0);
+                for region in Region::ALL {
+                    ui.radio_value(&mut self.region_choice, region, region.name());
+                }
+
+                let preset = self.region_choice.preset();
+                ui.add_space(8.0);
+                egui::Grid::new("region_preset").num_columns(2).show(ui, |ui| {
+                    ui.label("Units");
+                    ui.label(preset.units.full_name());
+                    ui.end_row();
+                    ui.label("Paper");
+                    ui.label(format!("{:?}, plots on {:?}", preset.paper_size, preset.plot_paper));
+                    ui.end_row();
+                    ui.label("Dimensions");
+                    ui.label(preset.dimension_standard.style_name());
+                    ui.end_row();
+                    ui.label("Dates");
+                    ui.label(chrono::Utc::now().format(&preset.date_format).to_string());
+                    ui.end_row();
+                });
+
+                ui.add_space(8.0);
+                if ui.button("Continue").clicked() {
+                    chosen = Some(self.region_choice);
+                }
+            });
+        if let Some(region) = chosen {
+            self.set_region(region);
         }
     }
 
@@ -98,7 +192,7 @@ impl CaddyApp {
         use crate::ui::shortcuts::StandardShortcuts;
 
         // Collect which shortcuts were triggered
-        let (new_doc, open_doc, save_doc, save_as, undo_cmd, redo_cmd, toggle_grid, toggle_ortho, toggle_snap) =
+        let (new_doc, open_doc, save_doc, save_as, undo_cmd, redo_cmd, toggle_grid, toggle_ortho, toggle_snap, cycle_isoplane, toggle_polar) =
             ctx.input_mut(|i| {
                 (
                     i.consume_shortcut(&StandardShortcuts::NEW.into()),
@@ -110,6 +204,8 @@ impl CaddyApp {
                     i.key_pressed(egui::Key::F7),
                     i.key_pressed(egui::Key::F8),
                     i.key_pressed(egui::Key::F9),
+                    i.key_pressed(egui::Key::F5),
+                    i.key_pressed(egui::Key::F10),
                 )
             });
 
@@ -140,11 +236,17 @@ impl CaddyApp {
             self.ui_state.show_grid = !self.ui_state.show_grid;
         }
         if toggle_ortho {
-            self.ui_state.ortho_mode = !self.ui_state.ortho_mode;
+            self.ui_state.toggle_ortho();
         }
         if toggle_snap {
             self.ui_state.snap_to_grid = !self.ui_state.snap_to_grid;
         }
+        if cycle_isoplane {
+            self.ui_state.cycle_isoplane();
+        }
+        if toggle_polar {
+            self.ui_state.toggle_polar();
+        }
 
         // Escape to cancel current command
         let escape_pressed = ctx.input(|i| i.key_pressed(egui::Key::Escape));
@@ -207,6 +309,12 @@ impl CaddyApp {
     }
 
     /// Execute a command from the command line
+    /// Block library browser, to point at the preview service the open
+    /// drawing is published to
+    pub fn block_library_mut(&mut self) -> &mut BlockLibraryPanel {
+        &mut self.block_library_panel
+    }
+
     pub fn execute_command(&mut self, command: &str) {
         log::info!("Executing command: {}", command);
         self.active_command = Some(command.to_string());
@@ -222,7 +330,10 @@ impl CaddyApp {
             "PAN" | "P" => self.start_pan_command(),
             "GRID" => self.ui_state.show_grid = !self.ui_state.show_grid,
             "SNAP" => self.ui_state.snap_to_grid = !self.ui_state.snap_to_grid,
-            "ORTHO" => self.ui_state.ortho_mode = !self.ui_state.ortho_mode,
+            "ORTHO" => self.ui_state.toggle_ortho(),
+            "POLAR" => self.ui_state.toggle_polar(),
+            "ISOPLANE" => self.ui_state.cycle_isoplane(),
+            "PURGE" | "AUDIT" | "SIMPLIFY" => self.start_cleanup_command(command),
             _ => {
                 log::warn!("Unknown command: {}", command);
                 self.command_line.set_error(&format!("Unknown command: {}", command));
@@ -235,6 +346,13 @@ impl CaddyApp {
         self.command_line.set_prompt("Specify first point:");
     }
 
+    fn start_insert_command(&mut self, block: &str) {
+        log::info!("Starting INSERT command for block {}", block);
+        self.active_command = Some("INSERT".to_string());
+        self.command_panel.add_command(&format!("INSERT {}", block));
+        self.command_line.set_prompt(&format!("Specify insertion point for {}:", block));
+    }
+
     fn start_circle_command(&mut self) {
         log::info!("Starting CIRCLE command");
         self.command_line.set_prompt("Specify center point:");
@@ -250,6 +368,11 @@ impl CaddyApp {
         self.command_line.set_prompt("Specify first corner:");
     }
 
+    fn start_cleanup_command(&mut self, command: &str) {
+        log::info!("Starting {} command", command.to_uppercase());
+        self.command_line.set_prompt("Select objects or <All>:");
+    }
+
     fn start_pan_command(&mut self) {
         log::info!("Starting PAN command");
         self.ui_state.mode = crate::ui::InteractionMode::Pan;
@@ -263,6 +386,10 @@ impl eframe::App for CaddyApp {
         // Handle keyboard shortcuts
         self.handle_shortcuts(ctx);
 
+        if self.region.is_none() {
+            self.show_region_window(ctx);
+        }
+
         // Top menu bar
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
@@ -321,6 +448,8 @@ impl eframe::App for CaddyApp {
                     ui.checkbox(&mut self.ui_state.show_grid, "Grid\tF7");
                     ui.checkbox(&mut self.ui_state.snap_to_grid, "Snap\tF9");
                     ui.checkbox(&mut self.ui_state.ortho_mode, "Ortho\tF8");
+                    ui.checkbox(&mut self.ui_state.polar_tracking, "Polar\tF10");
+                    ui.checkbox(&mut self.ui_state.isometric, "Isometric");
                     ui.separator();
                     if ui.button("Zoom Extents\tCtrl+E").clicked() {
                         self.canvas.zoom_extents();
@@ -330,6 +459,14 @@ impl eframe::App for CaddyApp {
                     ui.checkbox(&mut self.ui_state.show_layers, "Layers Panel");
                     ui.checkbox(&mut self.ui_state.show_properties, "Properties Panel");
                     ui.checkbox(&mut self.ui_state.show_command_history, "Command History");
+                    ui.checkbox(&mut self.ui_state.show_recycle_bin, "Recycle Bin");
+                    ui.checkbox(&mut self.ui_state.show_drawing_health, "Drawing Health");
+                    ui.checkbox(&mut self.ui_state.show_block_library, "Block Library");
+                    ui.separator();
+                    if ui.button("Drafting Region...").clicked() {
+                        self.region = None;
+                        ui.close_menu();
+                    }
                 });
 
                 ui.menu_button("Draw", |ui| {
@@ -454,6 +591,48 @@ impl eframe::App for CaddyApp {
                 });
         }
 
+        // Recycle bin panel (right side)
+        if self.ui_state.show_recycle_bin {
+            egui::SidePanel::right("recycle_bin_panel")
+                .resizable(true)
+                .default_width(250.0)
+                .show(ctx, |ui| {
+                    self.recycle_bin_panel.show(ui, &mut self.ui_state);
+                });
+
+            if !self.recycle_bin_panel.take_restored().is_empty() {
+                self.document_modified = true;
+            }
+        }
+
+        // Drawing health panel (right side)
+        if self.ui_state.show_drawing_health {
+            egui::SidePanel::right("drawing_health_panel")
+                .resizable(true)
+                .default_width(300.0)
+                .show(ctx, |ui| {
+                    self.drawing_health_panel.show(ui, &mut self.ui_state);
+                });
+
+            if let Some(remediation) = self.drawing_health_panel.take_remediation() {
+                self.execute_command(remediation.command());
+            }
+        }
+
+        // Block library panel (right side)
+        if self.ui_state.show_block_library {
+            egui::SidePanel::right("block_library_panel")
+                .resizable(true)
+                .default_width(260.0)
+                .show(ctx, |ui| {
+                    self.block_library_panel.show(ui, &mut self.ui_state);
+                });
+
+            if let Some(block) = self.block_library_panel.take_insert() {
+                self.start_insert_command(&block);
+            }
+        }
+
         // Command line (bottom)
         egui::TopBottomPanel::bottom("command_line")
             .resizable(false)
@@ -481,8 +660,12 @@ impl eframe::App for CaddyApp {
         ctx.request_repaint();
     }
 
-    fn save(&mut self, _storage: &mut dyn eframe::Storage) {
-        // Save application state
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        if let Some(region) = self.region {
+            if let Ok(value) = serde_json::to_string(&region) {
+                storage.set_string(REGION_KEY, value);
+            }
+        }
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
@@ -291,6 +291,7 @@ impl Canvas {
             if response.clicked_by(egui::PointerButton::Primary) {
                 log::info!("Canvas clicked at world: ({:.2}, {:.2})", world_pos.x, world_pos.y);
                 self.mouse_down_pos = Some(mouse_pos);
+                state.last_point = Some(state.cursor_pos);
             }
 
             // Handle right click (context menu)
@@ -3,6 +3,8 @@
 /// AutoCAD-style command line with autocomplete, history, and coordinate parsing.
 use egui::{Ui, TextEdit, Color32, RichText, Key};
 use super::UiState;
+use crate::io::parameters::ParameterValues;
+use crate::io::units::Unit;
 
 /// Command line widget
 pub struct CommandLine {
@@ -28,6 +30,8 @@ pub struct CommandLine {
     max_output: usize,
     /// Command registry for autocomplete
     command_registry: Vec<CommandInfo>,
+    /// Parameters and drawing units typed numbers are evaluated with
+    values: ParameterValues,
 }
 
 #[derive(Debug, Clone)]
@@ -65,6 +69,7 @@ impl CommandLine {
             output_lines: Vec::new(),
             max_output: 100,
             command_registry: Vec::new(),
+            values: ParameterValues::empty(Unit::Millimeters),
         };
 
         cmd_line.initialize_commands();
@@ -224,6 +229,11 @@ impl CommandLine {
         self.error = None;
     }
 
+    /// Evaluate typed numbers over the document's parameters, in its drawing units
+    pub fn set_values(&mut self, values: ParameterValues) {
+        self.values = values;
+    }
+
     /// Add line to output
     fn add_output(&mut self, text: &str, line_type: OutputType) {
         self.output_lines.push(OutputLine {
@@ -316,31 +326,7 @@ impl CommandLine {
 
     /// Parse coordinate input (e.g., "100,200" or "100<45" for polar)
     fn parse_coordinate(&self, input: &str) -> Option<(f64, f64)> {
-        let input = input.trim();
-
-        // Cartesian coordinates: "x,y"
-        if let Some((x_str, y_str)) = input.split_once(',') {
-            if let (Ok(x), Ok(y)) = (x_str.trim().parse::<f64>(), y_str.trim().parse::<f64>()) {
-                return Some((x, y));
-            }
-        }
-
-        // Polar coordinates: "distance<angle"
-        if let Some((dist_str, angle_str)) = input.split_once('<') {
-            if let (Ok(dist), Ok(angle)) = (dist_str.trim().parse::<f64>(), angle_str.trim().parse::<f64>()) {
-                let angle_rad = angle.to_radians();
-                let x = dist * angle_rad.cos();
-                let y = dist * angle_rad.sin();
-                return Some((x, y));
-            }
-        }
-
-        // Relative coordinates: "@x,y"
-        if input.starts_with('@') {
-            return self.parse_coordinate(&input[1..]);
-        }
-
-        None
+        CoordinateParser::parse_with(input, &self.values).map(|input| input.to_absolute(None))
     }
 
     /// Show command line widget
@@ -470,47 +456,62 @@ impl Default for CommandLine {
 }
 
 /// Coordinate parser for various input formats
+///
+/// Each value may be an expression, such as `@25.4mm*3,width/2` or
+/// `1ft<360/8`, evaluated like any other numeric input.
 pub struct CoordinateParser;
 
 impl CoordinateParser {
-    /// Parse various coordinate formats
+    /// Parse various coordinate formats, with lengths in millimeters
     pub fn parse(input: &str) -> Option<CoordinateInput> {
+        Self::parse_with(input, &ParameterValues::empty(Unit::Millimeters))
+    }
+
+    /// Parse various coordinate formats, evaluating values over `values`
+    pub fn parse_with(input: &str, values: &ParameterValues) -> Option<CoordinateInput> {
         let input = input.trim();
+        let (relative, input) = match input.strip_prefix('@') {
+            Some(rest) => (true, rest),
+            None => (false, input),
+        };
+        let pair = |separator| {
+            let (first, second) = split_top_level(input, separator)?;
+            Some((values.evaluate(first.trim()).ok()?, values.evaluate(second.trim()).ok()?))
+        };
 
-        // Absolute Cartesian: "100,200"
-        if let Some((x_str, y_str)) = input.split_once(',') {
-            if let (Ok(x), Ok(y)) = (x_str.trim().parse::<f64>(), y_str.trim().parse::<f64>()) {
-                return Some(CoordinateInput::Absolute(x, y));
-            }
+        // Cartesian: "100,200" or "@100,200"
+        if let Some((x, y)) = pair(',') {
+            return Some(match relative {
+                true => CoordinateInput::Relative(x, y),
+                false => CoordinateInput::Absolute(x, y),
+            });
         }
 
-        // Relative Cartesian: "@100,200"
-        if input.starts_with('@') {
-            if let Some((x_str, y_str)) = input[1..].split_once(',') {
-                if let (Ok(x), Ok(y)) = (x_str.trim().parse::<f64>(), y_str.trim().parse::<f64>()) {
-                    return Some(CoordinateInput::Relative(x, y));
-                }
-            }
+        // Polar: "100<45" or "@100<45"
+        if let Some((dist, angle)) = pair('<') {
+            return Some(match relative {
+                true => CoordinateInput::RelativePolar(dist, angle),
+                false => CoordinateInput::Polar(dist, angle),
+            });
         }
 
-        // Absolute Polar: "100<45"
-        if let Some((dist_str, angle_str)) = input.split_once('<') {
-            if let (Ok(dist), Ok(angle)) = (dist_str.trim().parse::<f64>(), angle_str.trim().parse::<f64>()) {
-                return Some(CoordinateInput::Polar(dist, angle));
-            }
-        }
+        None
+    }
+}
 
-        // Relative Polar: "@100<45"
-        if input.starts_with('@') {
-            if let Some((dist_str, angle_str)) = input[1..].split_once('<') {
-                if let (Ok(dist), Ok(angle)) = (dist_str.trim().parse::<f64>(), angle_str.trim().parse::<f64>()) {
-                    return Some(CoordinateInput::RelativePolar(dist, angle));
-                }
-            }
+/// Split at the first `separator` outside parentheses, so function
+/// arguments stay together
+fn split_top_level(input: &str, separator: char) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    for (i, c) in input.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if c == separator && depth == 0 => return Some((&input[..i], &input[i + c.len_utf8()..])),
+            _ => {}
         }
-
-        None
     }
+    None
 }
 
 /// Coordinate input types
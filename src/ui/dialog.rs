@@ -3,6 +3,10 @@
 /// Provides modal dialogs for file operations, settings, and configuration.
 use egui::{Window, Context, Color32, RichText};
 use super::UiState;
+use crate::io::import::{ImageImporter, ImageImportSettings};
+use crate::io::parameters::{ParameterError, ParameterValues, Parameters};
+use crate::io::units::Unit;
+use crate::io::vectorize::{RasterImage, TraceMode};
 use std::path::PathBuf;
 
 /// Base dialog trait
@@ -31,6 +35,8 @@ pub enum DialogData {
     Settings(SettingsData),
     Layer(LayerData),
     DimensionStyle(DimensionStyleData),
+    ImageTrace(ImageImportSettings),
+    Parameters(Parameters),
 }
 
 /// Settings data
@@ -62,6 +68,11 @@ pub struct DimensionStyleData {
     pub extension_line_offset: f64,
 }
 
+/// Parse a length typed into a drag value, allowing expressions like `1in + 5`
+fn length_input(text: &str) -> Option<f64> {
+    ParameterValues::empty(Unit::Millimeters).evaluate(text.trim()).ok()
+}
+
 /// File dialog for opening and saving files
 pub struct FileDialog {
     open: bool,
@@ -298,12 +309,14 @@ impl Dialog for SettingsDialog {
                         .show(ui, |ui| {
                             ui.label("Grid Size:");
                             ui.add(egui::DragValue::new(&mut self.settings.grid_size)
+                                .custom_parser(length_input)
                                 .speed(0.1)
                                 .clamp_range(1.0..=100.0));
                             ui.end_row();
 
                             ui.label("Snap Distance:");
                             ui.add(egui::DragValue::new(&mut self.settings.snap_distance)
+                                .custom_parser(length_input)
                                 .speed(0.1)
                                 .clamp_range(1.0..=50.0));
                             ui.end_row();
@@ -570,6 +583,7 @@ impl Dialog for DimensionStyleDialog {
                         .show(ui, |ui| {
                             ui.label("Text Height:");
                             ui.add(egui::DragValue::new(&mut self.style.text_height)
+                                .custom_parser(length_input)
                                 .speed(0.1)
                                 .clamp_range(0.5..=10.0));
                             ui.end_row();
@@ -586,6 +600,7 @@ impl Dialog for DimensionStyleDialog {
                         .show(ui, |ui| {
                             ui.label("Arrow Size:");
                             ui.add(egui::DragValue::new(&mut self.style.arrow_size)
+                                .custom_parser(length_input)
                                 .speed(0.1)
                                 .clamp_range(0.5..=10.0));
                             ui.end_row();
@@ -602,6 +617,7 @@ impl Dialog for DimensionStyleDialog {
                         .show(ui, |ui| {
                             ui.label("Extension Line Offset:");
                             ui.add(egui::DragValue::new(&mut self.style.extension_line_offset)
+                                .custom_parser(length_input)
                                 .speed(0.1)
                                 .clamp_range(0.0..=5.0));
                             ui.end_row();
@@ -645,6 +661,315 @@ impl Dialog for DimensionStyleDialog {
     }
 }
 
+/// Image tracing dialog with a live threshold/despeckle preview
+pub struct ImageTraceDialog {
+    open: bool,
+    image: RasterImage,
+    settings: ImageImportSettings,
+    preview: Option<egui::TextureHandle>,
+    ink_pixels: usize,
+    specks_removed: usize,
+}
+
+impl ImageTraceDialog {
+    pub fn new(image: RasterImage, settings: ImageImportSettings) -> Self {
+        Self {
+            open: true,
+            image,
+            settings: ImageImportSettings { vectorize: true, ..settings },
+            preview: None,
+            ink_pixels: 0,
+            specks_removed: 0,
+        }
+    }
+
+    /// Re-threshold the image and upload the result
+    fn refresh_preview(&mut self, ctx: &Context) {
+        let preview = ImageImporter::new(self.settings.clone()).preview(&self.image);
+        self.ink_pixels = preview.bitmap.ink_pixels();
+        self.specks_removed = preview.specks_removed;
+
+        let size = [self.image.width as usize, self.image.height as usize];
+        let image = egui::ColorImage::from_rgb(size, &preview.bitmap.to_rgb());
+        self.preview = Some(ctx.load_texture("image_trace_preview", image, egui::TextureOptions::NEAREST));
+    }
+}
+
+impl Dialog for ImageTraceDialog {
+    fn show(&mut self, ctx: &Context, _state: &mut UiState) -> DialogResult {
+        let mut result = DialogResult::None;
+        let mut should_close = false;
+        let mut changed = self.preview.is_none();
+
+        Window::new("Trace Image")
+            .open(&mut self.open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(440.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("image_trace")
+                    .num_columns(2)
+                    .spacing([40.0, 8.0])
+                    .show(ui, |ui| {
+                        ui.label("Trace:");
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut self.settings.mode, TraceMode::Outline, "Outlines");
+                            ui.radio_value(&mut self.settings.mode, TraceMode::Centerline, "Centerlines");
+                        });
+                        ui.end_row();
+
+                        ui.label("Threshold:");
+                        changed |= ui.add_enabled(
+                            self.settings.color_layers == 0,
+                            egui::Slider::new(&mut self.settings.threshold, 1..=255),
+                        ).changed();
+                        ui.end_row();
+
+                        ui.label("Despeckle:");
+                        ui.horizontal(|ui| {
+                            changed |= ui.add(egui::DragValue::new(&mut self.settings.despeckle)
+                                .clamp_range(0..=500))
+                                .changed();
+                            ui.label("px");
+                        });
+                        ui.end_row();
+
+                        ui.label("Color Layers:");
+                        changed |= ui.add(egui::DragValue::new(&mut self.settings.color_layers)
+                            .clamp_range(0..=8))
+                            .on_hover_text("0 traces a single black-and-white layer")
+                            .changed();
+                        ui.end_row();
+
+                        ui.label("Scale:");
+                        ui.add(egui::DragValue::new(&mut self.settings.scale)
+                            .speed(0.01)
+                            .clamp_range(0.0001..=1000.0));
+                        ui.end_row();
+                    });
+
+                ui.add_space(10.0);
+
+                if let Some(texture) = &self.preview {
+                    let size = texture.size_vec2();
+                    let fit = (420.0 / size.x).min(320.0 / size.y).min(1.0);
+                    ui.image(egui::load::SizedTexture::new(texture.id(), size * fit));
+                }
+                ui.label(RichText::new(format!(
+                    "{} ink pixels, {} specks removed",
+                    self.ink_pixels, self.specks_removed
+                )).color(Color32::GRAY));
+
+                ui.add_space(20.0);
+
+                // Buttons
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("  Trace  ").clicked() {
+                        result = DialogResult::Ok(DialogData::ImageTrace(self.settings.clone()));
+                        should_close = true;
+                    }
+
+                    if ui.button(" Cancel ").clicked() {
+                        result = DialogResult::Cancelled;
+                        should_close = true;
+                    }
+                });
+            });
+
+        if changed && self.open && !should_close {
+            self.refresh_preview(ctx);
+        }
+
+        if should_close {
+            self.open = false;
+        }
+
+        if !self.open && result == DialogResult::None {
+            result = DialogResult::Cancelled;
+        }
+
+        result
+    }
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+    }
+}
+
+/// One row of the parameter editor, as typed
+#[derive(Debug, Clone, PartialEq)]
+struct ParameterRow {
+    name: String,
+    expression: String,
+    unit: Option<Unit>,
+    description: String,
+}
+
+/// Document parameter table editor
+///
+/// Values are re-evaluated as rows are edited, so errors such as unknown
+/// names or cycles show before the table is applied.
+pub struct ParameterDialog {
+    open: bool,
+    rows: Vec<ParameterRow>,
+    drawing_units: Unit,
+}
+
+impl ParameterDialog {
+    pub fn new(parameters: &Parameters, drawing_units: Unit) -> Self {
+        Self {
+            open: true,
+            rows: parameters
+                .iter()
+                .map(|p| ParameterRow {
+                    name: p.name.clone(),
+                    expression: p.expression.source().to_string(),
+                    unit: p.unit,
+                    description: p.description.clone(),
+                })
+                .collect(),
+            drawing_units,
+        }
+    }
+
+    /// Table from the rows, or the first problem with them
+    fn parameters(&self) -> Result<Parameters, ParameterError> {
+        let mut parameters = Parameters::new();
+        for row in &self.rows {
+            let name = row.name.trim();
+            if parameters.get(name).is_some() {
+                return Err(ParameterError::Duplicate(name.to_string()));
+            }
+            parameters.set(name, &row.expression, row.unit)?;
+            parameters.describe(name, row.description.clone());
+        }
+        parameters.evaluate(self.drawing_units)?;
+        Ok(parameters)
+    }
+}
+
+impl Dialog for ParameterDialog {
+    fn show(&mut self, ctx: &Context, _state: &mut UiState) -> DialogResult {
+        let mut result = DialogResult::None;
+        let mut should_close = false;
+
+        let checked = self.parameters();
+        let values = checked
+            .as_ref()
+            .ok()
+            .and_then(|parameters| parameters.evaluate(self.drawing_units).ok());
+
+        Window::new("Parameters")
+            .open(&mut self.open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                let mut remove = None;
+                egui::ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+                    egui::Grid::new("parameters")
+                        .num_columns(6)
+                        .spacing([12.0, 6.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("Name");
+                            ui.strong("Expression");
+                            ui.strong("Unit");
+                            ui.strong("Value");
+                            ui.strong("Description");
+                            ui.label("");
+                            ui.end_row();
+
+                            for (index, row) in self.rows.iter_mut().enumerate() {
+                                ui.add(egui::TextEdit::singleline(&mut row.name).desired_width(100.0));
+                                ui.add(egui::TextEdit::singleline(&mut row.expression).desired_width(160.0));
+                                egui::ComboBox::from_id_source(("parameter_unit", index))
+                                    .selected_text(row.unit.map_or("-", |u| u.abbreviation()))
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(&mut row.unit, None, "None");
+                                        for unit in [Unit::Millimeters, Unit::Centimeters, Unit::Meters,
+                                                     Unit::Inches, Unit::Feet] {
+                                            ui.selectable_value(&mut row.unit, Some(unit), unit.full_name());
+                                        }
+                                    });
+                                let value = values
+                                    .as_ref()
+                                    .and_then(|values| values.format(row.name.trim(), None));
+                                ui.label(value.unwrap_or_else(|| "-".to_string()));
+                                ui.add(egui::TextEdit::singleline(&mut row.description).desired_width(140.0));
+                                if ui.small_button("Remove").clicked() {
+                                    remove = Some(index);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+                if let Some(index) = remove {
+                    self.rows.remove(index);
+                }
+
+                if ui.button("Add Parameter").clicked() {
+                    let name = (1..)
+                        .map(|n| format!("p{}", n))
+                        .find(|name| self.rows.iter().all(|row| row.name.trim() != name))
+                        .expect("unused parameter name");
+                    self.rows.push(ParameterRow {
+                        name,
+                        expression: "0".to_string(),
+                        unit: Some(self.drawing_units),
+                        description: String::new(),
+                    });
+                }
+
+                ui.add_space(10.0);
+                if let Err(error) = &checked {
+                    ui.label(RichText::new(error.to_string()).color(Color32::LIGHT_RED));
+                }
+
+                ui.add_space(10.0);
+
+                // Buttons
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(checked.is_ok(), egui::Button::new("   OK   ")).clicked() {
+                        if let Ok(parameters) = &checked {
+                            result = DialogResult::Ok(DialogData::Parameters(parameters.clone()));
+                            should_close = true;
+                        }
+                    }
+
+                    if ui.button(" Cancel ").clicked() {
+                        result = DialogResult::Cancelled;
+                        should_close = true;
+                    }
+                });
+            });
+
+        if should_close {
+            self.open = false;
+        }
+
+        if !self.open && result == DialogResult::None {
+            result = DialogResult::Cancelled;
+        }
+
+        result
+    }
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+    }
+}
+
 /// About dialog
 pub struct AboutDialog {
     open: bool,
@@ -5,7 +5,7 @@
 /// - Main application window and event loop
 /// - Drawing toolbars (line, arc, circle, rectangle, etc.)
 /// - Modify toolbars (move, copy, rotate, scale, etc.)
-/// - Side panels (properties, layers, command history)
+/// - Side panels (properties, layers, command history, block library)
 /// - Command line interface (AutoCAD-style)
 /// - Drawing canvas with mouse/keyboard input
 /// - Status bar with coordinate display
@@ -23,12 +23,16 @@ pub mod status_bar;
 pub use app::CaddyApp;
 pub use window::MainWindow;
 pub use toolbar::{Toolbar, DrawToolbar, ModifyToolbar, ViewToolbar, ToolbarPosition};
-pub use panel::{PropertiesPanel, LayersPanel, CommandPanel, Panel};
-pub use dialog::{FileDialog, SettingsDialog, LayerDialog, DimensionStyleDialog, Dialog};
+pub use panel::{
+    PropertiesPanel, LayersPanel, CommandPanel, RecycleBinPanel, DrawingHealthPanel, BlockLibraryPanel, Panel,
+};
+pub use dialog::{FileDialog, SettingsDialog, LayerDialog, DimensionStyleDialog, ImageTraceDialog, ParameterDialog, Dialog};
 pub use canvas::Canvas;
 pub use command_line::CommandLine;
 pub use status_bar::StatusBar;
 
+use crate::io::document::{DocumentSettings, GridType, Isoplane};
+
 /// UI theme and styling
 pub mod theme {
     use egui::{Color32, Style, Visuals, Stroke};
@@ -249,18 +253,36 @@ pub struct UiState {
     pub snap_to_grid: bool,
     /// Ortho mode (restrict to horizontal/vertical)
     pub ortho_mode: bool,
+    /// Polar tracking
+    pub polar_tracking: bool,
+    /// Polar tracking increment (degrees)
+    pub polar_increment: f64,
+    /// Grid snap spacing
+    pub grid_spacing: f64,
+    /// Isometric drafting
+    pub isometric: bool,
+    /// Active isoplane
+    pub isoplane: Isoplane,
     /// Show layer panel
     pub show_layers: bool,
     /// Show properties panel
     pub show_properties: bool,
     /// Show command panel
     pub show_command_history: bool,
+    /// Show recycle bin panel
+    pub show_recycle_bin: bool,
+    /// Show drawing health panel
+    pub show_drawing_health: bool,
+    /// Show block library panel
+    pub show_block_library: bool,
     /// Dark theme enabled
     pub dark_theme: bool,
     /// Current layer name
     pub current_layer: String,
     /// Cursor position in world coordinates
     pub cursor_pos: (f64, f64),
+    /// Last picked point, the base for polar coordinate readout
+    pub last_point: Option<(f64, f64)>,
 }
 
 impl Default for UiState {
@@ -270,12 +292,72 @@ impl Default for UiState {
             show_grid: true,
             snap_to_grid: false,
             ortho_mode: false,
+            polar_tracking: false,
+            polar_increment: 45.0,
+            grid_spacing: 1.0,
+            isometric: false,
+            isoplane: Isoplane::Top,
             show_layers: true,
             show_properties: true,
             show_command_history: true,
+            show_recycle_bin: false,
+            show_drawing_health: false,
+            show_block_library: false,
             dark_theme: true,
             current_layer: "0".to_string(),
             cursor_pos: (0.0, 0.0),
+            last_point: None,
+        }
+    }
+}
+
+impl UiState {
+    /// Toggle ortho mode (turns polar tracking off)
+    pub fn toggle_ortho(&mut self) {
+        self.ortho_mode = !self.ortho_mode;
+        if self.ortho_mode {
+            self.polar_tracking = false;
+        }
+    }
+
+    /// Toggle polar tracking (turns ortho mode off)
+    pub fn toggle_polar(&mut self) {
+        self.polar_tracking = !self.polar_tracking;
+        if self.polar_tracking {
+            self.ortho_mode = false;
+        }
+    }
+
+    /// Move to the next isoplane
+    pub fn cycle_isoplane(&mut self) {
+        self.isoplane = self.isoplane.next();
+    }
+
+    /// Take grid and snap settings from a document
+    pub fn load_settings(&mut self, settings: &DocumentSettings) {
+        self.show_grid = settings.grid.enabled;
+        self.snap_to_grid = settings.grid.snap;
+        self.grid_spacing = settings.grid.minor_spacing;
+        self.isometric = settings.grid.grid_type == GridType::Isometric;
+        self.isoplane = settings.grid.isoplane;
+        self.ortho_mode = settings.snap.ortho;
+        self.polar_tracking = settings.snap.polar;
+        self.polar_increment = settings.snap.polar_increment;
+    }
+
+    /// Save grid and snap settings into a document
+    pub fn store_settings(&self, settings: &mut DocumentSettings) {
+        settings.grid.enabled = self.show_grid;
+        settings.grid.snap = self.snap_to_grid;
+        settings.grid.minor_spacing = self.grid_spacing;
+        settings.grid.isoplane = self.isoplane;
+        if self.isometric {
+            settings.grid.grid_type = GridType::Isometric;
+        } else if settings.grid.grid_type == GridType::Isometric {
+            settings.grid.grid_type = GridType::Rectangular;
         }
+        settings.snap.ortho = self.ortho_mode;
+        settings.snap.polar = self.polar_tracking;
+        settings.snap.polar_increment = self.polar_increment;
     }
 }
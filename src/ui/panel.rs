@@ -2,7 +2,17 @@
 ///
 /// Provides side panels for managing layers, viewing properties, and command history.
 use egui::{Ui, ScrollArea, CollapsingHeader, Color32, RichText};
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
 use super::UiState;
+use crate::io::health::{HealthReport, HealthSeverity, Remediation};
+use crate::io::parameters::ParameterValues;
+use crate::io::trash::{RecycleBin, TrashItem};
+use crate::io::units::Unit;
+use crate::enterprise::compliance::access::{DocumentAccess, DocumentAccessSummary, DocumentAction};
+use crate::rendering::{PreviewService, PreviewSize, PreviewSubject};
 
 /// Base panel trait
 pub trait Panel {
@@ -16,6 +26,14 @@ pub struct PropertiesPanel {
     selected_count: usize,
     /// Property values (key-value pairs)
     properties: Vec<Property>,
+    /// Who has accessed the open document
+    access_summary: Option<DocumentAccessSummary>,
+    /// Most recent accesses, newest first
+    recent_access: Vec<DocumentAccess>,
+    /// Parameters and drawing units typed numbers are evaluated with
+    values: ParameterValues,
+    /// Numeric properties whose typed expression did not evaluate
+    invalid: HashSet<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -41,9 +59,69 @@ impl PropertiesPanel {
         Self {
             selected_count: 0,
             properties: Vec::new(),
+            access_summary: None,
+            recent_access: Vec::new(),
+            values: ParameterValues::empty(Unit::Millimeters),
+            invalid: HashSet::new(),
         }
     }
 
+    /// Evaluate typed numbers over the document's parameters, in its drawing units
+    pub fn set_values(&mut self, values: ParameterValues) {
+        self.values = values;
+    }
+
+    /// Show the open document's access history when nothing is selected
+    pub fn set_access_history(&mut self, summary: DocumentAccessSummary, recent: Vec<DocumentAccess>) {
+        self.access_summary = Some(summary);
+        self.recent_access = recent;
+    }
+
+    fn show_access_history(&self, ui: &mut Ui) {
+        let Some(summary) = &self.access_summary else {
+            return;
+        };
+
+        CollapsingHeader::new("Access History")
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::Grid::new("access_summary")
+                    .num_columns(2)
+                    .spacing([10.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for action in [DocumentAction::View, DocumentAction::Edit, DocumentAction::Export, DocumentAction::Share] {
+                            ui.label(format!("{:?}s", action));
+                            ui.label(summary.counts.get(&action).copied().unwrap_or(0).to_string());
+                            ui.end_row();
+                        }
+                        ui.label("Users");
+                        ui.label(summary.users.join(", "));
+                        ui.end_row();
+                        if let Some(edit) = &summary.last_edited {
+                            ui.label("Last edited");
+                            ui.label(format!("{} by {}", edit.timestamp.format("%Y-%m-%d %H:%M"), edit.user));
+                            ui.end_row();
+                        }
+                    });
+
+                if !self.recent_access.is_empty() {
+                    ui.separator();
+                    for access in &self.recent_access {
+                        ui.label(
+                            RichText::new(format!(
+                                "{}  {}  {}",
+                                access.timestamp.format("%Y-%m-%d %H:%M"),
+                                access.user,
+                                access.action.name(),
+                            ))
+                            .small(),
+                        );
+                    }
+                }
+            });
+    }
+
     /// Update properties for selected entities
     pub fn update_selection(&mut self, count: usize) {
         self.selected_count = count;
@@ -148,6 +226,7 @@ impl Panel for PropertiesPanel {
 
         if self.selected_count == 0 {
             ui.label("No selection");
+            self.show_access_history(ui);
             return;
         }
 
@@ -184,11 +263,26 @@ impl Panel for PropertiesPanel {
                         .spacing([10.0, 4.0])
                         .striped(true)
                         .show(ui, |ui| {
-                            for prop in &self.properties {
+                            for prop in &mut self.properties {
                                 if matches!(prop.property_type, PropertyType::Number) {
                                     ui.label(&prop.name);
                                     if prop.editable {
-                                        ui.text_edit_singleline(&mut prop.value.clone());
+                                        // Expressions such as `25.4mm*3 + 1in` are evaluated on commit
+                                        let error = self.invalid.contains(&prop.name)
+                                            .then_some(Color32::from_rgb(255, 100, 100));
+                                        let edit = egui::TextEdit::singleline(&mut prop.value)
+                                            .text_color_opt(error);
+                                        if ui.add(edit).lost_focus() {
+                                            match self.values.evaluate(prop.value.trim()) {
+                                                Ok(value) => {
+                                                    prop.value = value.to_string();
+                                                    self.invalid.remove(&prop.name);
+                                                }
+                                                Err(_) => {
+                                                    self.invalid.insert(prop.name.clone());
+                                                }
+                                            }
+                                        }
                                     } else {
                                         ui.label(&prop.value);
                                     }
@@ -538,6 +632,376 @@ impl Panel for CommandPanel {
     }
 }
 
+/// Recycle bin panel - lists deleted entities and documents for restore
+pub struct RecycleBinPanel {
+    bin: RecycleBin,
+    restored: Vec<TrashItem>,
+}
+
+impl RecycleBinPanel {
+    pub fn new() -> Self {
+        Self {
+            bin: RecycleBin::default(),
+            restored: Vec::new(),
+        }
+    }
+
+    /// Recycle bin backing the panel
+    pub fn bin_mut(&mut self) -> &mut RecycleBin {
+        &mut self.bin
+    }
+
+    /// Items restored since the last call, for the app to put back into
+    /// the drawing
+    pub fn take_restored(&mut self) -> Vec<TrashItem> {
+        std::mem::take(&mut self.restored)
+    }
+
+    fn restore(&mut self, item_id: Uuid) {
+        match self.bin.take(item_id) {
+            Ok(item) => {
+                log::info!("Restored {} {}", item.kind(), item.object_id());
+                self.restored.push(item);
+            }
+            Err(e) => log::warn!("Restore failed: {}", e),
+        }
+    }
+}
+
+impl Panel for RecycleBinPanel {
+    fn show(&mut self, ui: &mut Ui, _state: &mut UiState) {
+        ui.heading("Recycle Bin");
+        ui.separator();
+
+        ui.label(format!(
+            "{} items, kept for {} days",
+            self.bin.len(),
+            self.bin.settings().retention_days
+        ));
+
+        ui.separator();
+
+        let now = Utc::now();
+        let mut to_restore = None;
+
+        ScrollArea::vertical().show(ui, |ui| {
+            // Newest deletions first
+            for item in self.bin.items().iter().rev() {
+                ui.horizontal(|ui| {
+                    if ui.small_button("Restore").clicked() {
+                        to_restore = Some(item.id);
+                    }
+
+                    ui.label(item.label());
+                });
+
+                let days_left = (item.expires_at - now).num_days().max(0);
+                ui.label(RichText::new(format!(
+                    "{} by {} · {} days left",
+                    item.deleted_at.format("%Y-%m-%d %H:%M"),
+                    item.deleted_by,
+                    days_left
+                ))
+                .color(Color32::GRAY)
+                .size(10.0));
+
+                if let Some(reason) = &item.reason {
+                    ui.label(RichText::new(reason).italics().size(10.0));
+                }
+
+                ui.separator();
+            }
+        });
+
+        if let Some(item_id) = to_restore {
+            self.restore(item_id);
+        }
+    }
+
+    fn title(&self) -> &str {
+        "Recycle Bin"
+    }
+}
+
+/// Drawing health panel - profile of the active drawing with fixes
+pub struct DrawingHealthPanel {
+    report: Option<HealthReport>,
+    requested: Option<Remediation>,
+}
+
+impl DrawingHealthPanel {
+    pub fn new() -> Self {
+        Self {
+            report: None,
+            requested: None,
+        }
+    }
+
+    /// Show a new profile
+    pub fn set_report(&mut self, report: HealthReport) {
+        self.report = Some(report);
+    }
+
+    /// Remediation the user clicked, if any
+    pub fn take_remediation(&mut self) -> Option<Remediation> {
+        self.requested.take()
+    }
+
+    fn counts(ui: &mut Ui, id: &str, counts: &std::collections::BTreeMap<String, usize>) {
+        let mut counts: Vec<_> = counts.iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+
+        egui::Grid::new(id).num_columns(2).striped(true).show(ui, |ui| {
+            for (name, count) in counts {
+                ui.label(name);
+                ui.label(count.to_string());
+                ui.end_row();
+            }
+        });
+    }
+}
+
+impl Panel for DrawingHealthPanel {
+    fn show(&mut self, ui: &mut Ui, _state: &mut UiState) {
+        ui.heading("Drawing Health");
+        ui.separator();
+
+        let Some(report) = &self.report else {
+            ui.label(RichText::new("No profile yet").color(Color32::GRAY));
+            return;
+        };
+        let mut requested = None;
+
+        ui.label(format!(
+            "{} entities · {:.1} KB · ~{} segments",
+            report.entity_count,
+            report.file_size as f64 / 1024.0,
+            report.total_segments
+        ));
+
+        ScrollArea::vertical().show(ui, |ui| {
+            CollapsingHeader::new(format!("Issues ({})", report.issues.len()))
+                .default_open(true)
+                .show(ui, |ui| {
+                    if report.issues.is_empty() {
+                        ui.label(RichText::new("✓ No issues").color(Color32::GREEN));
+                    }
+                    for issue in &report.issues {
+                        ui.horizontal_wrapped(|ui| {
+                            match issue.severity {
+                                HealthSeverity::Warning => {
+                                    ui.label(RichText::new("⚠").color(Color32::YELLOW))
+                                }
+                                HealthSeverity::Info => {
+                                    ui.label(RichText::new("ℹ").color(Color32::LIGHT_BLUE))
+                                }
+                            };
+                            ui.label(&issue.message);
+                            if let Some(remediation) = issue.remediation {
+                                if ui.link(remediation.label()).clicked() {
+                                    requested = Some(remediation);
+                                }
+                            }
+                        });
+                    }
+                });
+
+            CollapsingHeader::new("Entities by type").show(ui, |ui| {
+                Self::counts(ui, "health_by_type", &report.by_type);
+            });
+
+            CollapsingHeader::new("Entities by layer").show(ui, |ui| {
+                Self::counts(ui, "health_by_layer", &report.by_layer);
+            });
+
+            CollapsingHeader::new("Heaviest blocks").show(ui, |ui| {
+                egui::Grid::new("health_blocks").num_columns(3).striped(true).show(ui, |ui| {
+                    ui.label(RichText::new("Block").strong());
+                    ui.label(RichText::new("Inserts").strong());
+                    ui.label(RichText::new("Segments").strong());
+                    ui.end_row();
+                    for block in &report.heaviest_blocks {
+                        ui.label(&block.name);
+                        ui.label(block.inserts.to_string());
+                        ui.label(block.total_segments().to_string());
+                        ui.end_row();
+                    }
+                });
+            });
+
+            CollapsingHeader::new("Regeneration hotspots").show(ui, |ui| {
+                for hotspot in &report.hotspots {
+                    ui.label(format!(
+                        "{} on {} — {} segments",
+                        hotspot.type_name, hotspot.layer, hotspot.segments
+                    ));
+                }
+            });
+
+            if let Some(undo) = &report.undo {
+                CollapsingHeader::new("Undo history").show(ui, |ui| {
+                    ui.label(format!(
+                        "{} levels, {:.1} KB",
+                        undo.levels,
+                        undo.bytes as f64 / 1024.0
+                    ));
+                });
+            }
+
+            CollapsingHeader::new("Unused definitions").show(ui, |ui| {
+                for layer in &report.unused_layers {
+                    ui.label(format!("Layer {}", layer));
+                }
+                for block in &report.unused_blocks {
+                    ui.label(format!("Block {}", block));
+                }
+            });
+        });
+
+        if requested.is_some() {
+            self.requested = requested;
+        }
+    }
+
+    fn title(&self) -> &str {
+        "Drawing Health"
+    }
+}
+
+/// Block library panel - thumbnails of the drawing's block definitions
+pub struct BlockLibraryPanel {
+    /// Preview service and the published document whose blocks are shown
+    source: Option<(Arc<PreviewService>, Uuid)>,
+    /// Uploaded thumbnails by block name, with the revision they show
+    textures: HashMap<String, (u64, egui::TextureHandle)>,
+    filter: String,
+    requested: Option<String>,
+}
+
+impl BlockLibraryPanel {
+    pub fn new() -> Self {
+        Self {
+            source: None,
+            textures: HashMap::new(),
+            filter: String::new(),
+            requested: None,
+        }
+    }
+
+    /// Browse the blocks of a document published to `previews`
+    pub fn set_source(&mut self, previews: Arc<PreviewService>, document: Uuid) {
+        if self.source.as_ref().map(|(_, id)| *id) != Some(document) {
+            self.textures.clear();
+        }
+        self.source = Some((previews, document));
+    }
+
+    /// Block the user picked to insert, if any
+    pub fn take_insert(&mut self) -> Option<String> {
+        self.requested.take()
+    }
+
+    /// Thumbnail of a block, uploaded again whenever its preview changed
+    fn texture(
+        &mut self,
+        ctx: &egui::Context,
+        previews: &PreviewService,
+        document: Uuid,
+        name: &str,
+    ) -> Option<egui::TextureId> {
+        let subject = PreviewSubject::Block(name.to_string());
+        let preview = match previews.preview(document, &subject, PreviewSize::Small) {
+            Ok(preview) => preview?,
+            Err(e) => {
+                log::warn!("Preview of block {} failed: {}", name, e);
+                return None;
+            }
+        };
+        match self.textures.get(name) {
+            Some((revision, texture)) if *revision == preview.revision => Some(texture.id()),
+            _ => {
+                let (width, height) = preview.image.dimensions();
+                let image = egui::ColorImage::from_rgba_unmultiplied(
+                    [width as usize, height as usize],
+                    preview.image.as_raw(),
+                );
+                let texture = ctx.load_texture(format!("block_preview_{}", name), image, Default::default());
+                let id = texture.id();
+                self.textures.insert(name.to_string(), (preview.revision, texture));
+                Some(id)
+            }
+        }
+    }
+}
+
+impl Panel for BlockLibraryPanel {
+    fn show(&mut self, ui: &mut Ui, _state: &mut UiState) {
+        ui.heading("Block Library");
+        ui.separator();
+
+        let Some((previews, document)) = self.source.clone() else {
+            ui.label(RichText::new("No drawing published").color(Color32::GRAY));
+            return;
+        };
+        let Some(doc) = previews.document(document) else {
+            ui.label(RichText::new("Drawing no longer published").color(Color32::GRAY));
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            ui.text_edit_singleline(&mut self.filter);
+        });
+        let filter = self.filter.to_lowercase();
+        let mut names: Vec<&String> = doc
+            .blocks
+            .keys()
+            .filter(|name| name.to_lowercase().contains(&filter))
+            .collect();
+        names.sort();
+        self.textures.retain(|name, _| doc.blocks.contains_key(name));
+
+        ui.label(format!("{} of {} blocks", names.len(), doc.blocks.len()));
+        ui.separator();
+
+        let edge = PreviewSize::Small.pixels() as f32;
+        ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("block_library")
+                .num_columns(3)
+                .spacing([8.0, 8.0])
+                .show(ui, |ui| {
+                    for (i, name) in names.iter().enumerate() {
+                        let block = &doc.blocks[*name];
+                        ui.vertical(|ui| {
+                            let clicked = match self.texture(ui.ctx(), &previews, document, name) {
+                                Some(id) => {
+                                    let image = egui::load::SizedTexture::new(id, [edge, edge]);
+                                    ui.add(egui::ImageButton::new(image)).clicked()
+                                }
+                                None => ui.add_sized([edge, edge], egui::Button::new("?")).clicked(),
+                            };
+                            if clicked {
+                                self.requested = Some(name.to_string());
+                            }
+                            let label = ui.label(RichText::new(name.as_str()).size(10.0));
+                            if !block.description.is_empty() {
+                                label.on_hover_text(block.description.as_str());
+                            }
+                        });
+
+                        if (i + 1) % 3 == 0 {
+                            ui.end_row();
+                        }
+                    }
+                });
+        });
+    }
+
+    fn title(&self) -> &str {
+        "Block Library"
+    }
+}
+
 /// Quick access panel for frequently used commands
 pub struct QuickAccessPanel {
     commands: Vec<QuickCommand>,
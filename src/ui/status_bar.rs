@@ -4,11 +4,16 @@
 /// ortho toggle, and current layer display.
 use egui::{Ui, Color32, RichText};
 use super::UiState;
+use crate::geometry::Point2D;
+use crate::io::readout::{Alignment, CoordinateMode, ReadoutFormat};
+use crate::io::units::Unit;
 
 /// Status bar widget
 pub struct StatusBar {
-    /// Show coordinate precision
-    coordinate_precision: usize,
+    /// Coordinate readout format
+    readout: ReadoutFormat,
+    /// Alignment for station/offset readout
+    alignment: Option<Alignment>,
     /// Units display
     units: Units,
     /// Show mode indicators
@@ -39,7 +44,11 @@ impl Units {
 impl StatusBar {
     pub fn new() -> Self {
         Self {
-            coordinate_precision: 2,
+            readout: ReadoutFormat {
+                precision: 2,
+                ..ReadoutFormat::default()
+            },
+            alignment: None,
             units: Units::Millimeters,
             show_mode_indicators: true,
         }
@@ -47,25 +56,46 @@ impl StatusBar {
 
     /// Set coordinate precision
     pub fn set_precision(&mut self, precision: usize) {
-        self.coordinate_precision = precision.min(6);
+        self.readout.precision = precision.min(6);
     }
 
-    /// Set units
-    pub fn set_units(&mut self, units: Units) {
-        self.units = units;
+    /// Set the coordinate readout format
+    pub fn set_readout(&mut self, readout: ReadoutFormat) {
+        self.readout = readout;
+    }
+
+    /// Current coordinate readout format
+    pub fn readout(&self) -> &ReadoutFormat {
+        &self.readout
+    }
+
+    /// Set the alignment used for station/offset readout
+    pub fn set_alignment(&mut self, alignment: Option<Alignment>) {
+        self.alignment = alignment;
+    }
+
+    /// Set the units shown from the drawing units
+    pub fn set_units(&mut self, units: Unit) {
+        self.units = match units {
+            Unit::Centimeters => Units::Centimeters,
+            Unit::Meters => Units::Meters,
+            Unit::Inches | Unit::Fractional => Units::Inches,
+            Unit::Feet | Unit::Engineering | Unit::Architectural => Units::Feet,
+            Unit::Millimeters | Unit::Decimal => Units::Millimeters,
+        };
     }
 
     /// Show status bar
     pub fn show(&mut self, ui: &mut Ui, state: &mut UiState) {
         ui.horizontal(|ui| {
             // Coordinate display
-            self.show_coordinates(ui, _state);
+            self.show_coordinates(ui, state);
 
             ui.separator();
 
             // Mode indicators
             if self.show_mode_indicators {
-                self.show_mode_indicators_ui(ui, _state);
+                self.show_mode_indicators_ui(ui, state);
             }
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -77,7 +107,7 @@ impl StatusBar {
                 ui.separator();
 
                 // Current layer
-                self.show_layer_indicator(ui, _state);
+                self.show_layer_indicator(ui, state);
 
                 ui.separator();
 
@@ -88,16 +118,46 @@ impl StatusBar {
     }
 
     /// Show coordinate display
-    fn show_coordinates(&self, ui: &mut Ui, state: &UiState) {
+    fn show_coordinates(&mut self, ui: &mut Ui, state: &UiState) {
         let (x, y) = state.cursor_pos;
 
+        // Clicking the mode label cycles XY -> polar -> station
+        let mode_response = ui.add(
+            egui::Button::new(
+                RichText::new(self.readout.coordinates.label())
+                    .color(Color32::from_rgb(150, 150, 150))
+                    .monospace()
+                    .size(11.0)
+            )
+            .frame(false)
+            .small()
+        );
+
+        if mode_response.clicked() {
+            self.readout.coordinates = self.readout.coordinates.next();
+        }
+
+        if mode_response.hovered() {
+            egui::show_tooltip(ui.ctx(), egui::Id::new("coordinate_mode_tooltip"), |ui| {
+                ui.label("Cycle coordinate display");
+            });
+        }
+
+        if self.readout.coordinates != CoordinateMode::Cartesian {
+            let base = state.last_point.map(|(x, y)| Point2D::new(x, y));
+            let text = self.readout.format_point(Point2D::new(x, y), base, self.alignment.as_ref());
+            ui.label(RichText::new(text)
+                .color(Color32::from_rgb(200, 200, 200))
+                .monospace());
+            return;
+        }
+
         // X coordinate
         ui.label(RichText::new("X:")
             .color(Color32::from_rgb(255, 100, 100))
             .strong());
 
-        let x_text = format!("{:.*}", self.coordinate_precision, x);
-        ui.label(RichText::new(x_text)
+        ui.label(RichText::new(self.readout.format_distance(x))
             .color(Color32::from_rgb(200, 200, 200))
             .monospace());
 
@@ -108,8 +168,7 @@ impl StatusBar {
             .color(Color32::from_rgb(100, 255, 100))
             .strong());
 
-        let y_text = format!("{:.*}", self.coordinate_precision, y);
-        ui.label(RichText::new(y_text)
+        ui.label(RichText::new(self.readout.format_distance(y))
             .color(Color32::from_rgb(200, 200, 200))
             .monospace());
 
@@ -120,7 +179,7 @@ impl StatusBar {
             .color(Color32::from_rgb(100, 100, 255))
             .strong());
 
-        ui.label(RichText::new(format!("{:.*}", self.coordinate_precision, 0.0))
+        ui.label(RichText::new(self.readout.format_distance(0.0))
             .color(Color32::from_rgb(200, 200, 200))
             .monospace());
     }
@@ -181,7 +240,7 @@ impl StatusBar {
 
         if snap_response.hovered() {
             egui::show_tooltip(ui.ctx(), egui::Id::new("snap_tooltip"), |ui| {
-                ui.label("Toggle Snap (F9)");
+                ui.label(format!("Toggle Snap (F9) - spacing {}", state.grid_spacing));
             });
         }
 
@@ -206,7 +265,7 @@ impl StatusBar {
         );
 
         if ortho_response.clicked() {
-            state.ortho_mode = !state.ortho_mode;
+            state.toggle_ortho();
         }
 
         if ortho_response.hovered() {
@@ -217,6 +276,46 @@ impl StatusBar {
 
         ui.separator();
 
+        // Isometric drafting, showing the active isoplane
+        let iso_color = if state.isometric {
+            Color32::from_rgb(100, 200, 100)
+        } else {
+            Color32::from_rgb(100, 100, 100)
+        };
+
+        let iso_text = if state.isometric {
+            format!("ISO {}", state.isoplane.name().to_uppercase())
+        } else {
+            "ISO".to_string()
+        };
+
+        let iso_response = ui.add(
+            egui::Button::new(
+                RichText::new(iso_text)
+                    .color(iso_color)
+                    .monospace()
+                    .size(11.0)
+            )
+            .frame(false)
+            .small()
+        );
+
+        if iso_response.clicked() {
+            state.isometric = !state.isometric;
+        }
+
+        if iso_response.secondary_clicked() {
+            state.cycle_isoplane();
+        }
+
+        if iso_response.hovered() {
+            egui::show_tooltip(ui.ctx(), egui::Id::new("iso_tooltip"), |ui| {
+                ui.label("Isometric Drafting (right-click or F5 to cycle isoplane)");
+            });
+        }
+
+        ui.separator();
+
         // Object snap (OSNAP)
         let osnap_color = Color32::from_rgb(100, 100, 100);
 
@@ -244,7 +343,11 @@ impl StatusBar {
         ui.separator();
 
         // Polar tracking
-        let polar_color = Color32::from_rgb(100, 100, 100);
+        let polar_color = if state.polar_tracking {
+            Color32::from_rgb(100, 200, 100)
+        } else {
+            Color32::from_rgb(100, 100, 100)
+        };
 
         let polar_response = ui.add(
             egui::Button::new(
@@ -258,12 +361,12 @@ impl StatusBar {
         );
 
         if polar_response.clicked() {
-            // Toggle polar tracking
+            state.toggle_polar();
         }
 
         if polar_response.hovered() {
             egui::show_tooltip(ui.ctx(), egui::Id::new("polar_tooltip"), |ui| {
-                ui.label("Polar Tracking (F10)");
+                ui.label(format!("Polar Tracking (F10) - every {}°", state.polar_increment));
             });
         }
 
@@ -0,0 +1,360 @@
+//! HTML canvas rendering
+//!
+//! Draws an `io::Document` into a `CanvasRenderingContext2d`. World
+//! coordinates are Y-up; the canvas is Y-down, so every point goes through a
+//! [`ViewTransform`] that fits the drawing extents into the canvas and flips Y.
+//! Text fields are evaluated on every draw, so they always show current values.
+
+use crate::io::document::{BoundingBox, Color, Document, Entity, GeometryType, Vec3, Vertex};
+use crate::io::fields::{evaluate, FieldContext};
+use std::f64::consts::PI;
+use wasm_bindgen::JsValue;
+use web_sys::CanvasRenderingContext2d;
+
+/// Margin around the drawing extents, as a fraction of the canvas size
+const FIT_MARGIN: f64 = 0.05;
+
+/// Most points drawn per point cloud; larger clouds are subsampled
+const CLOUD_PREVIEW_POINTS: usize = 50_000;
+
+/// World-to-canvas mapping (uniform scale, Y flipped)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewTransform {
+    /// Canvas pixels per drawing unit
+    pub scale: f64,
+    /// Canvas X of the world origin
+    pub offset_x: f64,
+    /// Canvas Y of the world origin
+    pub offset_y: f64,
+}
+
+impl ViewTransform {
+    /// Fit `bounds` into a `width` x `height` canvas, centered, with a small margin
+    pub fn fit(bounds: &BoundingBox, width: f64, height: f64) -> Self {
+        let size = bounds.size();
+        let usable_w = width * (1.0 - 2.0 * FIT_MARGIN);
+        let usable_h = height * (1.0 - 2.0 * FIT_MARGIN);
+
+        let scale = match (size.x > 0.0, size.y > 0.0) {
+            (true, true) => (usable_w / size.x).min(usable_h / size.y),
+            (true, false) => usable_w / size.x,
+            (false, true) => usable_h / size.y,
+            (false, false) => 1.0,
+        };
+
+        let center = bounds.center();
+        Self {
+            scale,
+            offset_x: width / 2.0 - center.x * scale,
+            offset_y: height / 2.0 + center.y * scale,
+        }
+    }
+
+    /// Map a world point to canvas pixels
+    pub fn apply(&self, p: Vec3) -> (f64, f64) {
+        (
+            self.offset_x + p.x * self.scale,
+            self.offset_y - p.y * self.scale,
+        )
+    }
+
+    /// Map a world length to canvas pixels
+    pub fn length(&self, d: f64) -> f64 {
+        d * self.scale
+    }
+}
+
+/// Renders documents into a 2D canvas context
+pub struct CanvasRenderer<'a> {
+    ctx: &'a CanvasRenderingContext2d,
+    fields: FieldContext,
+}
+
+impl<'a> CanvasRenderer<'a> {
+    /// Create a renderer for the given context
+    pub fn new(ctx: &'a CanvasRenderingContext2d) -> Self {
+        Self {
+            ctx,
+            fields: FieldContext::new(),
+        }
+    }
+
+    /// Evaluate text fields with `fields` (file name, sheet and project values)
+    pub fn with_fields(mut self, fields: FieldContext) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// Clear the canvas and draw all visible entities, zoomed to extents
+    pub fn render(&self, doc: &Document, width: f64, height: f64) -> Result<(), JsValue> {
+        self.ctx
+            .set_fill_style_str(&css_color(&doc.settings.background_color));
+        self.ctx.fill_rect(0.0, 0.0, width, height);
+
+        let bounds = match doc.bounding_box() {
+            Some(b) if b.min.x.is_finite() => b,
+            _ => return Ok(()),
+        };
+        let view = ViewTransform::fit(&bounds, width, height);
+
+        self.ctx.set_line_width(1.0);
+        for entity in &doc.entities {
+            if !entity.visible {
+                continue;
+            }
+            let layer = doc.get_layer(&entity.layer);
+            if layer.map(|l| !l.visible || l.frozen).unwrap_or(false) {
+                continue;
+            }
+
+            let color = entity
+                .color
+                .or_else(|| layer.map(|l| l.color))
+                .unwrap_or_else(Color::white);
+            let css = css_color(&color);
+            self.ctx.set_stroke_style_str(&css);
+            self.ctx.set_fill_style_str(&css);
+
+            self.draw_entity(entity, doc, &view)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_entity(&self, entity: &Entity, doc: &Document, view: &ViewTransform) -> Result<(), JsValue> {
+        let ctx = self.ctx;
+        match &entity.geometry {
+            GeometryType::Point(p) => {
+                let (x, y) = view.apply(p.position);
+                ctx.fill_rect(x - 1.5, y - 1.5, 3.0, 3.0);
+            }
+            GeometryType::Line(l) => {
+                let (x0, y0) = view.apply(l.start);
+                let (x1, y1) = view.apply(l.end);
+                ctx.begin_path();
+                ctx.move_to(x0, y0);
+                ctx.line_to(x1, y1);
+                ctx.stroke();
+            }
+            GeometryType::Circle(c) => {
+                let (x, y) = view.apply(c.center);
+                ctx.begin_path();
+                ctx.arc(x, y, view.length(c.radius), 0.0, 2.0 * PI)?;
+                ctx.stroke();
+            }
+            GeometryType::Arc(a) => {
+                let (x, y) = view.apply(a.center);
+                // Flipping Y turns counter-clockwise world arcs into
+                // anticlockwise canvas arcs with negated angles.
+                ctx.begin_path();
+                ctx.arc_with_anticlockwise(
+                    x,
+                    y,
+                    view.length(a.radius),
+                    -a.start_angle,
+                    -a.end_angle,
+                    true,
+                )?;
+                ctx.stroke();
+            }
+            GeometryType::Ellipse(e) => {
+                let (x, y) = view.apply(e.center);
+                ctx.begin_path();
+                ctx.ellipse(
+                    x,
+                    y,
+                    view.length(e.major_axis),
+                    view.length(e.minor_axis),
+                    -e.rotation,
+                    0.0,
+                    2.0 * PI,
+                )?;
+                ctx.stroke();
+            }
+            GeometryType::Polyline(p) => {
+                self.draw_polyline(&p.vertices, p.closed, view)?;
+            }
+            GeometryType::Spline(s) => {
+                // Control polygon; accurate evaluation lives in `geometry::curve`
+                let vertices: Vec<Vertex> = s
+                    .control_points
+                    .iter()
+                    .map(|&position| Vertex { position, bulge: 0.0 })
+                    .collect();
+                self.draw_polyline(&vertices, s.closed, view)?;
+            }
+            GeometryType::Text(t) => {
+                let text = evaluate(&t.text, doc, &self.fields);
+                self.draw_text(&text, t.position, t.height, t.rotation, view)?;
+            }
+            GeometryType::MText(t) => {
+                let text = evaluate(&t.text, doc, &self.fields);
+                self.draw_text(&text, t.position, t.height, t.rotation, view)?;
+            }
+            GeometryType::Hatch(h) => {
+                for boundary in &h.boundaries {
+                    let vertices: Vec<Vertex> = boundary
+                        .iter()
+                        .map(|&position| Vertex { position, bulge: 0.0 })
+                        .collect();
+                    self.draw_polyline(&vertices, true, view)?;
+                }
+            }
+            GeometryType::PointCloud(cloud) => {
+                let step = cloud.len().div_ceil(CLOUD_PREVIEW_POINTS).max(1);
+                for p in cloud.points().step_by(step) {
+                    let (x, y) = view.apply(Vec3::new(p.x, p.y, p.z));
+                    ctx.fill_rect(x - 0.5, y - 0.5, 1.0, 1.0);
+                }
+            }
+            GeometryType::Dimension(_) | GeometryType::Insert(_) => {}
+        }
+        Ok(())
+    }
+
+    fn draw_polyline(
+        &self,
+        vertices: &[Vertex],
+        closed: bool,
+        view: &ViewTransform,
+    ) -> Result<(), JsValue> {
+        if vertices.len() < 2 {
+            return Ok(());
+        }
+
+        let ctx = self.ctx;
+        ctx.begin_path();
+        let (x, y) = view.apply(vertices[0].position);
+        ctx.move_to(x, y);
+
+        let segment_count = if closed {
+            vertices.len()
+        } else {
+            vertices.len() - 1
+        };
+        for i in 0..segment_count {
+            let from = &vertices[i];
+            let to = &vertices[(i + 1) % vertices.len()];
+            match bulge_arc(from.position, to.position, from.bulge) {
+                Some((center, radius, start, end)) => {
+                    let (cx, cy) = view.apply(center);
+                    ctx.arc_with_anticlockwise(
+                        cx,
+                        cy,
+                        view.length(radius),
+                        -start,
+                        -end,
+                        from.bulge > 0.0,
+                    )?;
+                }
+                None => {
+                    let (x, y) = view.apply(to.position);
+                    ctx.line_to(x, y);
+                }
+            }
+        }
+
+        if closed {
+            ctx.close_path();
+        }
+        ctx.stroke();
+        Ok(())
+    }
+
+    fn draw_text(
+        &self,
+        text: &str,
+        position: Vec3,
+        height: f64,
+        rotation: f64,
+        view: &ViewTransform,
+    ) -> Result<(), JsValue> {
+        let ctx = self.ctx;
+        let (x, y) = view.apply(position);
+        ctx.save();
+        ctx.translate(x, y)?;
+        ctx.rotate(-rotation)?;
+        ctx.set_font(&format!("{}px sans-serif", view.length(height).max(1.0)));
+        ctx.fill_text(text, 0.0, 0.0)?;
+        ctx.restore();
+        Ok(())
+    }
+}
+
+/// Arc through a bulged polyline segment: `(center, radius, start_angle, end_angle)`
+///
+/// Returns `None` for straight segments.
+fn bulge_arc(from: Vec3, to: Vec3, bulge: f64) -> Option<(Vec3, f64, f64, f64)> {
+    if bulge.abs() < 1e-9 {
+        return None;
+    }
+
+    let dx = to.x - from.x;
+    let dy = to.y - from.y;
+    let chord = (dx * dx + dy * dy).sqrt();
+    if chord < 1e-12 {
+        return None;
+    }
+
+    let radius = chord * (1.0 + bulge * bulge) / (4.0 * bulge.abs());
+    // Signed distance from chord midpoint to center, left of the chord for positive bulge
+    let sagitta_offset = chord * (1.0 - bulge * bulge) / (4.0 * bulge);
+    let center = Vec3::new(
+        (from.x + to.x) / 2.0 - dy / chord * sagitta_offset,
+        (from.y + to.y) / 2.0 + dx / chord * sagitta_offset,
+        from.z,
+    );
+
+    let start = (from.y - center.y).atan2(from.x - center.x);
+    let end = (to.y - center.y).atan2(to.x - center.x);
+    Some((center, radius, start, end))
+}
+
+fn css_color(color: &Color) -> String {
+    format!("rgb({},{},{})", color.r, color.g, color.b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_centers_and_flips() {
+        let bounds = BoundingBox::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(100.0, 50.0, 0.0));
+        let view = ViewTransform::fit(&bounds, 200.0, 200.0);
+
+        let (cx, cy) = view.apply(Vec3::new(50.0, 25.0, 0.0));
+        assert!((cx - 100.0).abs() < 1e-9);
+        assert!((cy - 100.0).abs() < 1e-9);
+
+        // Higher world Y is further up the canvas
+        let (_, top) = view.apply(Vec3::new(0.0, 50.0, 0.0));
+        let (_, bottom) = view.apply(Vec3::new(0.0, 0.0, 0.0));
+        assert!(top < bottom);
+
+        // Width is the limiting dimension: 90% of 200px over 100 units
+        assert!((view.scale - 1.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fit_degenerate_bounds() {
+        let p = Vec3::new(5.0, 5.0, 0.0);
+        let view = ViewTransform::fit(&BoundingBox::from_point(p), 100.0, 100.0);
+        assert_eq!(view.scale, 1.0);
+        assert_eq!(view.apply(p), (50.0, 50.0));
+    }
+
+    #[test]
+    fn test_bulge_semicircle() {
+        // Bulge of 1 is a half circle
+        let (center, radius, start, end) =
+            bulge_arc(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0), 1.0).unwrap();
+        assert!((center.x - 1.0).abs() < 1e-9);
+        assert!(center.y.abs() < 1e-9);
+        assert!((radius - 1.0).abs() < 1e-9);
+        assert!((start.abs() - PI).abs() < 1e-9);
+        assert!(end.abs() < 1e-9);
+
+        assert!(bulge_arc(Vec3::zero(), Vec3::unit_x(), 0.0).is_none());
+    }
+}
@@ -0,0 +1,153 @@
+//! WebAssembly bindings
+//!
+//! A thin, JavaScript-friendly wrapper around the portable layers of the crate
+//! (`core`, `geometry`, and the `io` readers/writers). Built with:
+//!
+//! ```text
+//! wasm-pack build --target web -- --no-default-features --features wasm
+//! ```
+//!
+//! From JavaScript:
+//!
+//! ```text
+//! import init, { WasmDocument } from "./pkg/caddy.js";
+//!
+//! await init();
+//! const doc = WasmDocument.fromDxf(await file.text());
+//! doc.render(canvas.getContext("2d"), canvas.width, canvas.height);
+//! const svg = doc.toSvg(800, 600);
+//! ```
+
+pub mod canvas;
+
+pub use canvas::{CanvasRenderer, ViewTransform};
+
+use crate::io::document::Document;
+use crate::io::dxf::{DxfReader, DxfVersion, DxfWriter};
+use crate::io::export::{SvgExportSettings, SvgExporter};
+use crate::io::import::{SvgImportSettings, SvgImporter};
+use crate::io::native::JsonFormat;
+use wasm_bindgen::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+
+/// A CAD document handle exposed to JavaScript
+#[wasm_bindgen]
+pub struct WasmDocument {
+    doc: Document,
+}
+
+#[wasm_bindgen]
+impl WasmDocument {
+    /// Create an empty document
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmDocument {
+        Self {
+            doc: Document::new(),
+        }
+    }
+
+    /// Parse a document from DXF text
+    #[wasm_bindgen(js_name = fromDxf)]
+    pub fn from_dxf(text: &str) -> Result<WasmDocument, JsError> {
+        let doc = DxfReader::new().read(text.as_bytes())?;
+        Ok(Self { doc })
+    }
+
+    /// Parse a document from the CADDY JSON format (.cdyj)
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(text: &str) -> Result<WasmDocument, JsError> {
+        let doc = JsonFormat::new().from_string(text)?;
+        Ok(Self { doc })
+    }
+
+    /// Import a document from SVG markup
+    #[wasm_bindgen(js_name = fromSvg)]
+    pub fn from_svg(text: &str) -> Result<WasmDocument, JsError> {
+        let doc = SvgImporter::new(SvgImportSettings::default()).import_from_string(text)?;
+        Ok(Self { doc })
+    }
+
+    /// Serialize the document as DXF R2018 text
+    #[wasm_bindgen(js_name = toDxf)]
+    pub fn to_dxf(&self) -> Result<String, JsError> {
+        let mut out = Vec::new();
+        DxfWriter::new(DxfVersion::R2018).write(&self.doc, &mut out)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    /// Serialize the document as CADDY JSON
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<String, JsError> {
+        Ok(JsonFormat::new().to_string(&self.doc)?)
+    }
+
+    /// Export the document as SVG markup with the given output size
+    #[wasm_bindgen(js_name = toSvg)]
+    pub fn to_svg(&self, width: f64, height: f64) -> Result<String, JsError> {
+        let settings = SvgExportSettings {
+            width,
+            height,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        SvgExporter::new(settings).export_to_writer(&self.doc, &mut out)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    /// Document title
+    #[wasm_bindgen(getter)]
+    pub fn title(&self) -> String {
+        self.doc.metadata.title.clone()
+    }
+
+    /// Number of entities in model space
+    #[wasm_bindgen(js_name = entityCount)]
+    pub fn entity_count(&self) -> usize {
+        self.doc.entities.len()
+    }
+
+    /// Names of all layers, sorted
+    #[wasm_bindgen(js_name = layerNames)]
+    pub fn layer_names(&self) -> Vec<String> {
+        let mut names = self.doc.layer_names();
+        names.sort();
+        names
+    }
+
+    /// Drawing extents as `[minX, minY, maxX, maxY]`, or `undefined` when empty
+    pub fn bounds(&self) -> Option<Vec<f64>> {
+        self.doc
+            .bounding_box()
+            .map(|b| vec![b.min.x, b.min.y, b.max.x, b.max.y])
+    }
+
+    /// Draw the whole document into a 2D canvas context, zoomed to extents
+    pub fn render(
+        &self,
+        ctx: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+    ) -> Result<(), JsError> {
+        CanvasRenderer::new(ctx)
+            .render(&self.doc, width, height)
+            .map_err(|e| JsError::new(&format!("{:?}", e)))
+    }
+}
+
+impl Default for WasmDocument {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Document> for WasmDocument {
+    fn from(doc: Document) -> Self {
+        Self { doc }
+    }
+}
+
+/// Library version, for feature detection from JavaScript
+#[wasm_bindgen]
+pub fn version() -> String {
+    crate::VERSION.to_string()
+}